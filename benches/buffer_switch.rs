@@ -0,0 +1,104 @@
+//! Buffer switching benchmark
+//!
+//! Cycles through twenty large, syntax-highlighted buffers, each scrolled
+//! to a different place, and times rendering each one after switching to
+//! it. The first round lays every buffer out; after that the split keeps
+//! their views, so switching back to a buffer shouldn't lay out any line.
+//!
+//! Run with `cargo bench --bench buffer_switch`.
+
+use std::time::{Duration, Instant};
+
+use fresh::app::Editor;
+use fresh::config::Config;
+use fresh::config_io::DirectoryContext;
+use fresh::view::color_support::ColorCapability;
+use ratatui::backend::TestBackend;
+use ratatui::Terminal;
+
+const BUFFERS: usize = 20;
+const LINES: usize = 20_000;
+const ROUNDS: usize = 10;
+const WIDTH: u16 = 160;
+const HEIGHT: u16 = 50;
+
+fn content(buffer: usize) -> String {
+    (0..LINES)
+        .map(|i| {
+            format!(
+                "fn item_{buffer}_{i}(value: u64) -> u64 {{ value * {i} + {buffer} }} // line {i}\n"
+            )
+        })
+        .collect()
+}
+
+/// Switch to the next buffer and render it, returning the time taken and
+/// the number of lines laid out from scratch
+fn switch(editor: &mut Editor, terminal: &mut Terminal<TestBackend>) -> (Duration, usize) {
+    let start = Instant::now();
+    editor.next_buffer();
+    let before = editor.active_line_layout().lines_laid_out();
+    terminal.draw(|frame| editor.render(frame)).unwrap();
+    let elapsed = start.elapsed();
+    (
+        elapsed,
+        editor.active_line_layout().lines_laid_out() - before,
+    )
+}
+
+fn main() {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let mut editor = Editor::with_working_dir(
+        Config::default(),
+        WIDTH,
+        HEIGHT,
+        Some(temp_dir.path().to_path_buf()),
+        DirectoryContext::for_testing(temp_dir.path()),
+        false,
+        ColorCapability::TrueColor,
+    )
+    .unwrap();
+    let mut terminal = Terminal::new(TestBackend::new(WIDTH, HEIGHT)).unwrap();
+
+    for buffer in 0..BUFFERS {
+        let path = temp_dir.path().join(format!("file{}.rs", buffer));
+        std::fs::write(&path, content(buffer)).unwrap();
+        editor.open_file(&path).unwrap();
+        editor.goto_line_col(1 + buffer * LINES / BUFFERS, None);
+    }
+
+    let mut cold = Duration::ZERO;
+    let mut cold_lines = 0;
+    for _ in 0..BUFFERS {
+        let (elapsed, lines) = switch(&mut editor, &mut terminal);
+        cold += elapsed;
+        cold_lines += lines;
+    }
+
+    let mut warm = Duration::ZERO;
+    let mut warm_lines = 0;
+    for _ in 0..ROUNDS * BUFFERS {
+        let (elapsed, lines) = switch(&mut editor, &mut terminal);
+        warm += elapsed;
+        warm_lines += lines;
+    }
+
+    println!(
+        "{} buffers of {} lines, {}x{}",
+        BUFFERS, LINES, WIDTH, HEIGHT
+    );
+    println!(
+        "first switch   {:>12?}  {:>5} lines laid out",
+        cold / BUFFERS as u32,
+        cold_lines / BUFFERS
+    );
+    println!(
+        "later switches {:>12?}  {:>5} lines laid out",
+        warm / (ROUNDS * BUFFERS) as u32,
+        warm_lines / (ROUNDS * BUFFERS)
+    );
+    assert_eq!(
+        warm_lines, 0,
+        "switching back to unchanged buffers laid out lines"
+    );
+}
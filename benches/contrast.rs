@@ -0,0 +1,98 @@
+//! Minimum contrast benchmark
+//!
+//! Cost per frame of `ui.minimum_contrast` for a 200x60 screen of buffer
+//! text drawn in the dark theme's syntax colors on its usual backgrounds:
+//! no adjustment, adjusting every character, and the `ContrastCache`
+//! the renderer uses, which adjusts each color pair once.
+//!
+//! Run with `cargo bench --bench contrast`.
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use fresh::view::theme::{adjust_for_contrast, ContrastCache, Theme, TEXT_CONTRAST};
+use ratatui::style::Color;
+
+const COLUMNS: usize = 200;
+const ROWS: usize = 60;
+const FRAMES: usize = 200;
+
+/// The (foreground, background) of every character on a screen
+fn screen(theme: &Theme) -> Vec<(Color, Color)> {
+    let foregrounds = [
+        theme.editor_fg,
+        theme.syntax_keyword,
+        theme.syntax_string,
+        theme.syntax_comment,
+        theme.syntax_function,
+        theme.syntax_type,
+        theme.syntax_variable,
+        theme.syntax_constant,
+        theme.syntax_operator,
+        theme.diagnostic_error_fg,
+        theme.diagnostic_hint_fg,
+    ];
+    let backgrounds = [
+        theme.editor_bg,
+        theme.editor_bg,
+        theme.editor_bg,
+        theme.selection_bg,
+        theme.search_match_bg,
+    ];
+    (0..COLUMNS * ROWS)
+        .map(|i| {
+            let row = i / COLUMNS;
+            // Tokens a few characters long, selections a few rows high
+            (
+                foregrounds[(i / 5 + row) % foregrounds.len()],
+                backgrounds[(row / 4) % backgrounds.len()],
+            )
+        })
+        .collect()
+}
+
+fn per_frame(total: Duration) -> Duration {
+    total / FRAMES as u32
+}
+
+fn main() {
+    let theme = Theme::dark();
+    let cells = screen(&theme);
+    println!(
+        "{}x{} screen, {} frames, minimum contrast {}",
+        COLUMNS, ROWS, FRAMES, TEXT_CONTRAST
+    );
+
+    let start = Instant::now();
+    for _ in 0..FRAMES {
+        for &(fg, bg) in &cells {
+            black_box((fg, bg));
+        }
+    }
+    let baseline = per_frame(start.elapsed());
+
+    let start = Instant::now();
+    for _ in 0..FRAMES {
+        for &(fg, bg) in &cells {
+            black_box(adjust_for_contrast(fg, bg, TEXT_CONTRAST));
+        }
+    }
+    let uncached = per_frame(start.elapsed());
+
+    let mut cache = ContrastCache::new(Some(TEXT_CONTRAST));
+    let start = Instant::now();
+    for _ in 0..FRAMES {
+        for &(fg, bg) in &cells {
+            black_box(cache.foreground(fg, bg));
+        }
+    }
+    let cached = per_frame(start.elapsed());
+
+    println!("no adjustment   {:>12?} per frame", baseline);
+    println!("every character {:>12?} per frame", uncached);
+    println!(
+        "cached          {:>12?} per frame ({} color pairs)",
+        cached,
+        cache.len()
+    );
+}
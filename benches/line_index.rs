@@ -0,0 +1,111 @@
+//! Line index benchmark
+//!
+//! Compares the buffer's line index against scanning the text for line
+//! feeds, on a buffer of a million lines with edits spread through it:
+//! going to a line, and finding the line of a byte offset. Also times the
+//! edits themselves, which split the pieces holding the file.
+//!
+//! Run with `cargo bench --bench line_index`.
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use fresh::model::buffer::TextBuffer;
+
+const LINES: usize = 1_000_000;
+const EDITS: usize = 1_000;
+const QUERIES: usize = 1_000;
+
+fn content() -> Vec<u8> {
+    (0..LINES)
+        .flat_map(|i| format!("line {} of the benchmark buffer\n", i).into_bytes())
+        .collect()
+}
+
+/// Start of `line` found by counting line feeds from the start of the text
+fn scan_line_start(text: &[u8], line: usize) -> usize {
+    if line == 0 {
+        return 0;
+    }
+    text.iter()
+        .enumerate()
+        .filter(|(_, &b)| b == b'\n')
+        .nth(line - 1)
+        .map_or(text.len(), |(i, _)| i + 1)
+}
+
+/// Line of `offset` found by counting the line feeds before it
+fn scan_line_at(text: &[u8], offset: usize) -> usize {
+    text[..offset].iter().filter(|&&b| b == b'\n').count()
+}
+
+/// Lines spread over the whole buffer, far end included
+fn lines() -> Vec<usize> {
+    (0..QUERIES)
+        .map(|i| (i * 7919 + LINES - 1) % LINES)
+        .collect()
+}
+
+fn report(name: &str, before: Duration, after: Duration, runs: usize) {
+    let per_run = |total: Duration| total / runs as u32;
+    println!(
+        "{:<12} scan {:>12?}  index {:>12?}  ({:.0}x)",
+        name,
+        per_run(before),
+        per_run(after),
+        before.as_secs_f64() / after.as_secs_f64().max(f64::EPSILON)
+    );
+}
+
+fn main() {
+    let mut buffer = TextBuffer::from_bytes(content());
+    println!("{} lines, {} bytes", LINES, buffer.len());
+
+    let start = Instant::now();
+    for i in 0..EDITS {
+        let offset = (i * 104_729) % buffer.len();
+        buffer.insert_bytes(offset, b"edit\n".to_vec());
+    }
+    println!(
+        "{:<12} {:>12?} per edit",
+        "edit",
+        start.elapsed() / EDITS as u32
+    );
+
+    let text = buffer.to_string().unwrap().into_bytes();
+    let lines = lines();
+    for &line in lines.iter().take(10) {
+        assert_eq!(
+            buffer.line_start_offset(line),
+            Some(scan_line_start(&text, line))
+        );
+    }
+
+    let start = Instant::now();
+    for &line in &lines {
+        black_box(scan_line_start(&text, line));
+    }
+    let before = start.elapsed();
+    let start = Instant::now();
+    for &line in &lines {
+        black_box(buffer.line_start_offset(line));
+    }
+    let after = start.elapsed();
+    report("goto line", before, after, QUERIES);
+
+    let offsets: Vec<usize> = lines
+        .iter()
+        .map(|&line| buffer.line_start_offset(line).unwrap() + 3)
+        .collect();
+    let start = Instant::now();
+    for &offset in &offsets {
+        black_box(scan_line_at(&text, offset));
+    }
+    let before = start.elapsed();
+    let start = Instant::now();
+    for &offset in &offsets {
+        black_box(buffer.line_at_offset(offset));
+    }
+    let after = start.elapsed();
+    report("byte to line", before, after, QUERIES);
+}
@@ -0,0 +1,109 @@
+//! Multi-cursor typing benchmark
+//!
+//! Puts a cursor at the start of each of the first N lines of a file, as
+//! "select all occurrences" might, types a hundred characters and deletes
+//! them again. One keystroke applies one batch of edits, so the time per
+//! cursor should stay about the same as the number of cursors grows.
+//!
+//! Run with `cargo bench --bench multi_cursor`.
+
+use std::time::{Duration, Instant};
+
+use crossterm::event::{KeyCode, KeyModifiers};
+use fresh::app::Editor;
+use fresh::config::Config;
+use fresh::config_io::DirectoryContext;
+use fresh::model::cursor::Cursor;
+use fresh::view::color_support::ColorCapability;
+
+const LINES: usize = 5_000;
+const CURSORS: [usize; 4] = [500, 1_000, 2_000, 5_000];
+const KEYSTROKES: usize = 100;
+/// How much more a cursor may cost with the most cursors than with the
+/// fewest; edits costing the square of the cursors would be ten times more
+const MAX_SLOWDOWN: f64 = 3.0;
+
+/// Open the file with a cursor at the start of each of its first `cursors`
+/// lines
+fn editor_with_cursors(temp_dir: &std::path::Path, cursors: usize) -> Editor {
+    let mut editor = Editor::with_working_dir(
+        Config::default(),
+        120,
+        40,
+        Some(temp_dir.to_path_buf()),
+        DirectoryContext::for_testing(temp_dir),
+        false,
+        ColorCapability::TrueColor,
+    )
+    .unwrap();
+    let path = temp_dir.join("items.txt");
+    let content: String = (0..LINES).map(|i| format!("item {i} = value\n")).collect();
+    std::fs::write(&path, &content).unwrap();
+    editor.open_file(&path).unwrap();
+
+    let state = editor.active_state_mut();
+    let mut line_start = 0;
+    for line in content.lines().take(cursors - 1) {
+        line_start += line.len() + 1;
+        state.cursors.add(Cursor::new(line_start));
+    }
+    editor
+}
+
+/// Type `KEYSTROKES` characters and delete them again, returning the time
+/// taken by each
+fn type_and_delete(editor: &mut Editor) -> (Duration, Duration) {
+    let start = Instant::now();
+    for _ in 0..KEYSTROKES {
+        editor
+            .handle_key(KeyCode::Char('x'), KeyModifiers::NONE)
+            .unwrap();
+    }
+    let typing = start.elapsed();
+
+    let start = Instant::now();
+    for _ in 0..KEYSTROKES {
+        editor
+            .handle_key(KeyCode::Backspace, KeyModifiers::NONE)
+            .unwrap();
+    }
+    (typing, start.elapsed())
+}
+
+fn per_cursor(elapsed: Duration, cursors: usize) -> Duration {
+    elapsed / (KEYSTROKES * cursors) as u32
+}
+
+fn main() {
+    let mut results = Vec::new();
+    for cursors in CURSORS {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let mut editor = editor_with_cursors(temp_dir.path(), cursors);
+        assert_eq!(editor.active_state().cursors.count(), cursors);
+        let before = editor.active_state().buffer.len();
+
+        let (typing, deleting) = type_and_delete(&mut editor);
+        assert_eq!(editor.active_state().buffer.len(), before);
+        println!(
+            "{:>5} cursors  typing {:>10?}/key {:>8?}/cursor  deleting {:>10?}/key {:>8?}/cursor",
+            cursors,
+            typing / KEYSTROKES as u32,
+            per_cursor(typing, cursors),
+            deleting / KEYSTROKES as u32,
+            per_cursor(deleting, cursors),
+        );
+        results.push((cursors, typing + deleting));
+    }
+
+    let (fewest, first) = results[0];
+    let (most, last) = results[results.len() - 1];
+    let slowdown = per_cursor(last, most).as_secs_f64() / per_cursor(first, fewest).as_secs_f64();
+    println!("time per cursor grew {:.2}x", slowdown);
+    assert!(
+        slowdown < MAX_SLOWDOWN,
+        "editing with {} cursors cost {:.2}x as much per cursor as with {}",
+        most,
+        slowdown,
+        fewest
+    );
+}
@@ -0,0 +1,164 @@
+//! Overlay store benchmark
+//!
+//! Compares `OverlayManager` against the linear scan it replaced, a plain
+//! list of overlays, with 50k overlays spread over ten namespaces: clearing
+//! one namespace, and finding the overlays in a screenful of text.
+//!
+//! Run with `cargo bench --bench overlays`.
+
+use std::hint::black_box;
+use std::ops::Range;
+use std::time::{Duration, Instant};
+
+use fresh::model::marker::MarkerList;
+use fresh::view::overlay::{Overlay, OverlayFace, OverlayManager, OverlayNamespace};
+use ratatui::style::Color;
+
+const OVERLAYS: usize = 50_000;
+const NAMESPACES: usize = 10;
+/// Bytes between the starts of consecutive overlays
+const SPACING: usize = 100;
+/// Bytes in a screenful of text
+const VIEWPORT: usize = 8_000;
+const QUERIES: usize = 1_000;
+const CLEARS: usize = 20;
+
+/// The overlays before the store: one list, scanned in full for every query
+#[derive(Default)]
+struct OverlayList {
+    overlays: Vec<Overlay>,
+}
+
+impl OverlayList {
+    fn clear_namespace(&mut self, namespace: &OverlayNamespace, marker_list: &mut MarkerList) {
+        self.overlays.retain(|overlay| {
+            if overlay.namespace.as_ref() == Some(namespace) {
+                marker_list.delete(overlay.start_marker);
+                marker_list.delete(overlay.end_marker);
+                false
+            } else {
+                true
+            }
+        });
+    }
+
+    fn query_viewport(&self, range: &Range<usize>, marker_list: &MarkerList) -> usize {
+        self.overlays
+            .iter()
+            .filter(|overlay| overlay.overlaps(range, marker_list))
+            .count()
+    }
+}
+
+fn namespaces() -> Vec<OverlayNamespace> {
+    (0..NAMESPACES)
+        .map(|i| OverlayNamespace::from_string(format!("bench-{}", i)))
+        .collect()
+}
+
+/// The ranges of the overlays: mostly short, every hundredth spanning a
+/// hundred overlays after it
+fn overlay_range(i: usize) -> Range<usize> {
+    let start = i * SPACING;
+    let len = if i % 100 == 0 { SPACING * 100 } else { 20 };
+    start..start + len
+}
+
+fn new_overlay(marker_list: &mut MarkerList, i: usize, namespaces: &[OverlayNamespace]) -> Overlay {
+    Overlay::with_namespace(
+        marker_list,
+        overlay_range(i),
+        OverlayFace::Background { color: Color::Red },
+        namespaces[i % NAMESPACES].clone(),
+    )
+}
+
+fn build_store(namespaces: &[OverlayNamespace]) -> (OverlayManager, MarkerList) {
+    let mut marker_list = MarkerList::new();
+    let mut manager = OverlayManager::new();
+    for i in 0..OVERLAYS {
+        let overlay = new_overlay(&mut marker_list, i, namespaces);
+        manager.add(overlay, &marker_list);
+    }
+    (manager, marker_list)
+}
+
+fn build_list(namespaces: &[OverlayNamespace]) -> (OverlayList, MarkerList) {
+    let mut marker_list = MarkerList::new();
+    let mut list = OverlayList::default();
+    for i in 0..OVERLAYS {
+        list.overlays
+            .push(new_overlay(&mut marker_list, i, namespaces));
+    }
+    (list, marker_list)
+}
+
+/// Viewport starts spread over the whole buffer
+fn viewports() -> Vec<Range<usize>> {
+    let buffer_len = OVERLAYS * SPACING;
+    (0..QUERIES)
+        .map(|i| {
+            let start = (i * 7919 * SPACING) % (buffer_len - VIEWPORT);
+            start..start + VIEWPORT
+        })
+        .collect()
+}
+
+fn report(name: &str, before: Duration, after: Duration, runs: usize) {
+    let per_run = |total: Duration| total / runs as u32;
+    println!(
+        "{:<16} list {:>12?}  store {:>12?}  ({:.0}x)",
+        name,
+        per_run(before),
+        per_run(after),
+        before.as_secs_f64() / after.as_secs_f64().max(f64::EPSILON)
+    );
+}
+
+fn bench_clear_namespace(namespaces: &[OverlayNamespace]) {
+    let mut before = Duration::ZERO;
+    let mut after = Duration::ZERO;
+    for run in 0..CLEARS {
+        let namespace = &namespaces[run % NAMESPACES];
+
+        let (mut list, mut marker_list) = build_list(namespaces);
+        let start = Instant::now();
+        list.clear_namespace(namespace, &mut marker_list);
+        before += start.elapsed();
+        black_box(list.overlays.len());
+
+        let (mut manager, mut marker_list) = build_store(namespaces);
+        let start = Instant::now();
+        manager.clear_namespace(namespace, &mut marker_list);
+        after += start.elapsed();
+        black_box(manager.len());
+    }
+    report("clear_namespace", before, after, CLEARS);
+}
+
+fn bench_viewport_query(namespaces: &[OverlayNamespace]) {
+    let viewports = viewports();
+
+    let (list, marker_list) = build_list(namespaces);
+    let start = Instant::now();
+    for viewport in &viewports {
+        black_box(list.query_viewport(viewport, &marker_list));
+    }
+    let before = start.elapsed();
+
+    let (manager, marker_list) = build_store(namespaces);
+    let start = Instant::now();
+    for viewport in &viewports {
+        black_box(manager.query_viewport(viewport.start, viewport.end, &marker_list));
+    }
+    let after = start.elapsed();
+
+    report("viewport query", before, after, QUERIES);
+}
+
+fn main() {
+    let namespaces = namespaces();
+    println!("{} overlays in {} namespaces", OVERLAYS, NAMESPACES);
+    bench_clear_namespace(&namespaces);
+    bench_viewport_query(&namespaces);
+}
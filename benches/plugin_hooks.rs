@@ -0,0 +1,84 @@
+//! Plugin hook dispatch benchmark
+//!
+//! Emits 10,000 `cursor_moved` events to three plugin handlers that do
+//! nothing, and as many events nobody listens to, timing the cost per
+//! event of handing the hook data to the handlers. Run it on an older
+//! commit too to compare; `emit` takes the same arguments there.
+//!
+//! Run with `cargo bench --bench plugin_hooks`.
+
+use std::time::{Duration, Instant};
+
+use fresh::model::event::{BufferId, CursorId};
+use fresh::services::plugins::hooks::{hook_args_to_json, HookArgs};
+use fresh::services::plugins::runtime::TypeScriptRuntime;
+
+const EVENTS: usize = 10_000;
+
+fn cursor_moved(i: usize) -> HookArgs {
+    HookArgs::CursorMoved {
+        buffer_id: BufferId(1),
+        cursor_id: CursorId(0),
+        old_position: i,
+        new_position: i + 1,
+        line: 1 + i / 80,
+    }
+}
+
+/// Time emitting `EVENTS` cursor_moved events named `event_name`
+async fn emit_all(runtime: &mut TypeScriptRuntime, event_name: &str) -> Duration {
+    let start = Instant::now();
+    for i in 0..EVENTS {
+        let data = hook_args_to_json(&cursor_moved(i)).unwrap();
+        runtime.emit(event_name, &data).await.unwrap();
+    }
+    start.elapsed()
+}
+
+fn main() {
+    let tokio_runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap();
+    tokio_runtime.block_on(async {
+        let mut runtime = TypeScriptRuntime::new().unwrap();
+        runtime
+            .execute_script(
+                "<bench_setup>",
+                r#"
+                const editor = globalThis._createPluginEditor('bench');
+                globalThis.noop1 = function(data) {};
+                globalThis.noop2 = function(data) {};
+                globalThis.noop3 = function(data) {};
+                editor.on("cursor_moved", "noop1");
+                editor.on("cursor_moved", "noop2");
+                editor.on("cursor_moved", "noop3");
+                "#,
+            )
+            .await
+            .unwrap();
+
+        // Warm up
+        emit_all(&mut runtime, "cursor_moved").await;
+
+        let handled = emit_all(&mut runtime, "cursor_moved").await;
+        let unhandled = emit_all(&mut runtime, "cursor_moved_unheard").await;
+        // Let the handlers' promises settle
+        runtime
+            .execute_script("<bench_done>", "void 0")
+            .await
+            .unwrap();
+
+        println!("{} cursor_moved events", EVENTS);
+        println!(
+            "3 no-op handlers {:>12?}  {:>10?}/event",
+            handled,
+            handled / EVENTS as u32
+        );
+        println!(
+            "no handlers      {:>12?}  {:>10?}/event",
+            unhandled,
+            unhandled / EVENTS as u32
+        );
+    });
+}
@@ -0,0 +1,108 @@
+//! Virtual buffer panel benchmark
+//!
+//! Times the text properties of a panel of 50k entries, such as workspace
+//! diagnostics: looking up the properties under the cursor as it moves
+//! through the panel, against the linear scan it replaced, and building the
+//! panel in chunks by appending, against setting the whole content again
+//! for each chunk.
+//!
+//! Run with `cargo bench --bench virtual_buffer_panels`.
+
+use std::collections::HashMap;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use fresh::primitives::text_property::{TextProperty, TextPropertyEntry, TextPropertyManager};
+
+const ENTRIES: usize = 50_000;
+const CHUNK: usize = 2_000;
+const LOOKUPS: usize = 10_000;
+
+fn entry(i: usize) -> TextPropertyEntry {
+    let mut properties = HashMap::new();
+    properties.insert("index".to_string(), serde_json::json!(i));
+    properties.insert(
+        "location".to_string(),
+        serde_json::json!({ "file": format!("src/module_{}.rs", i % 97), "line": i }),
+    );
+    TextPropertyEntry {
+        text: format!(
+            "src/module_{}.rs:{}: warning: unused variable `x{}`\n",
+            i % 97,
+            i,
+            i
+        ),
+        properties,
+    }
+}
+
+fn entries(range: std::ops::Range<usize>) -> Vec<TextPropertyEntry> {
+    range.map(entry).collect()
+}
+
+/// Lookup before binary search: every property checked
+fn get_at_linear(properties: &[TextProperty], pos: usize) -> Vec<&TextProperty> {
+    properties.iter().filter(|p| p.contains(pos)).collect()
+}
+
+fn report(name: &str, before: Duration, after: Duration, runs: usize) {
+    let per_run = |total: Duration| total / runs as u32;
+    println!(
+        "{:<16} before {:>12?}  after {:>12?}  ({:.0}x)",
+        name,
+        per_run(before),
+        per_run(after),
+        before.as_secs_f64() / after.as_secs_f64().max(f64::EPSILON)
+    );
+}
+
+fn bench_lookup() {
+    let (text, manager) = TextPropertyManager::from_entries(entries(0..ENTRIES));
+    let positions: Vec<usize> = (0..LOOKUPS).map(|i| (i * 7919 * 61) % text.len()).collect();
+
+    let start = Instant::now();
+    for &pos in &positions {
+        black_box(get_at_linear(manager.all(), pos));
+    }
+    let before = start.elapsed();
+
+    let start = Instant::now();
+    for &pos in &positions {
+        black_box(manager.get_at(pos));
+    }
+    let after = start.elapsed();
+    report("get_at", before, after, LOOKUPS);
+}
+
+fn bench_chunked_build() {
+    let chunks = ENTRIES / CHUNK;
+
+    // Before: each chunk set the whole content so far again
+    let start = Instant::now();
+    for sent in 1..=chunks {
+        let (text, manager) = TextPropertyManager::from_entries(entries(0..sent * CHUNK));
+        black_box((text.len(), manager.len()));
+    }
+    let before = start.elapsed();
+
+    // After: each chunk is appended at the end
+    let start = Instant::now();
+    let mut text = String::new();
+    let mut manager = TextPropertyManager::new();
+    for sent in 0..chunks {
+        let (chunk_text, chunk) =
+            TextPropertyManager::from_entries(entries(sent * CHUNK..(sent + 1) * CHUNK));
+        let end = text.len();
+        manager.splice(end..end, chunk_text.len(), chunk);
+        text.push_str(&chunk_text);
+    }
+    black_box((text.len(), manager.len()));
+    let after = start.elapsed();
+    report("chunked build", before, after, 1);
+}
+
+fn main() {
+    println!("{} entries, chunks of {}", ENTRIES, CHUNK);
+    bench_lookup();
+    bench_chunked_build();
+}
@@ -175,7 +175,7 @@ fn rust_type_to_ts(rust_type: &str) -> String {
 
     match rust_type {
         // Primitives
-        "u32" | "u8" | "usize" | "i32" | "i64" | "u64" | "f32" | "f64" => "number".to_string(),
+        "u32" | "u16" | "u8" | "usize" | "i32" | "i64" | "u64" | "f32" | "f64" => "number".to_string(),
         "bool" => "boolean".to_string(),
         "String" | "&str" => "string".to_string(),
         "()" => "void".to_string(),
@@ -186,6 +186,15 @@ fn rust_type_to_ts(rust_type: &str) -> String {
         "TsBufferInfo" => "BufferInfo".to_string(),
         "TsCursorInfo" => "CursorInfo".to_string(),
         "TsViewportInfo" => "ViewportInfo".to_string(),
+        "TsTerminalInfo" => "TerminalInfo".to_string(),
+        "TsPromptState" => "PromptState".to_string(),
+        "TsFileExplorerState" => "FileExplorerState".to_string(),
+        "TsActionResult" => "ActionResult".to_string(),
+        "TsKeymapBinding" => "KeymapBinding".to_string(),
+        "TsShadowedBinding" => "ShadowedBinding".to_string(),
+        "TsLineCol" => "LineCol".to_string(),
+        "TsDiagnosticsSummary" => "DiagnosticsSummary".to_string(),
+        "TsFileDiagnosticCounts" => "FileDiagnosticCounts".to_string(),
         "TsSelectionRange" => "SelectionRange".to_string(),
         "TsSuggestion" => "PromptSuggestion".to_string(),
         "DirEntry" => "DirEntry".to_string(),
@@ -194,6 +203,7 @@ fn rust_type_to_ts(rust_type: &str) -> String {
             "CreateVirtualBufferInExistingSplitOptions".to_string()
         }
         "TsTextPropertyEntry" => "TextPropertyEntry".to_string(),
+        "TsRecentProject" => "RecentProject".to_string(),
 
         // Serde JSON value
         "serde_json::Value" => "unknown".to_string(),
@@ -547,9 +557,19 @@ fn parse_struct(lines: &[&str], struct_line_idx: usize) -> Option<StructInfo> {
         "TsBufferInfo" => "BufferInfo".to_string(),
         "TsCursorInfo" => "CursorInfo".to_string(),
         "TsViewportInfo" => "ViewportInfo".to_string(),
+        "TsTerminalInfo" => "TerminalInfo".to_string(),
+        "TsPromptState" => "PromptState".to_string(),
+        "TsFileExplorerState" => "FileExplorerState".to_string(),
+        "TsActionResult" => "ActionResult".to_string(),
+        "TsKeymapBinding" => "KeymapBinding".to_string(),
+        "TsShadowedBinding" => "ShadowedBinding".to_string(),
+        "TsLineCol" => "LineCol".to_string(),
+        "TsDiagnosticsSummary" => "DiagnosticsSummary".to_string(),
+        "TsFileDiagnosticCounts" => "FileDiagnosticCounts".to_string(),
         "TsSelectionRange" => "SelectionRange".to_string(),
         "TsSuggestion" => "PromptSuggestion".to_string(),
         "TsTextPropertyEntry" => "TextPropertyEntry".to_string(),
+        "TsRecentProject" => "RecentProject".to_string(),
         _ => name.clone(),
     };
 
@@ -613,12 +633,10 @@ fn parse_struct_field(line: &str, doc_comment: &str) -> Option<FieldInfo> {
         return None;
     }
 
-    // Strip "pub " prefix from field name if present
+    // Strip "pub " prefix and raw identifier marker from field name if present
     let raw_name = parts[0].trim();
-    let name = raw_name
-        .strip_prefix("pub ")
-        .unwrap_or(raw_name)
-        .to_string();
+    let raw_name = raw_name.strip_prefix("pub ").unwrap_or(raw_name);
+    let name = raw_name.strip_prefix("r#").unwrap_or(raw_name).to_string();
     let rust_type = parts[1].trim();
 
     let is_optional = rust_type.starts_with("Option<");
@@ -755,6 +773,29 @@ interface EditorAPI {
 "#,
     );
 
+    // Add startProgress (JS-only method that wraps the progress ops)
+    output.push_str(
+        r#"
+  /**
+   * Show the progress of a long operation and return a handle to report it
+   *
+   * With the handle, `update(50)` sets the percentage, `update("file.rs")` the
+   * message, `onCancel("handlerName")` names the function called when the user
+   * cancels, and `finish()` removes the entry. Until a percentage is given a
+   * spinner shows. Entries left unfinished are finished with a warning when the
+   * plugin unloads or stops updating them for a minute.
+   * @param options - Title and whether the user may cancel it
+   * @example
+   * const progress = await editor.startProgress({ title: "Indexing", cancellable: true });
+   * globalThis.onIndexCancelled = () => { cancelled = true; };
+   * progress.onCancel("onIndexCancelled");
+   * progress.update(40);
+   * progress.finish();
+   */
+  startProgress(options: TsProgressOptions): Promise<ProgressHandle>;
+"#,
+    );
+
     add_category_ops(&mut output, "Overlay Operations", &categories["overlay"]);
     add_category_ops(
         &mut output,
@@ -1090,7 +1131,7 @@ fn categorize_op(js_name: &str, is_async: bool) -> &'static str {
     }
 
     // Environment operations
-    if js_name == "getEnv" || js_name == "getCwd" {
+    if js_name == "getEnv" || js_name == "getCwd" || js_name == "getRecentProjects" {
         return "environment";
     }
 
@@ -1100,6 +1141,9 @@ fn categorize_op(js_name: &str, is_async: bool) -> &'static str {
         || js_name == "fileExists"
         || js_name == "fileStat"
         || js_name == "readDir"
+        || js_name == "compareDirectories"
+        || js_name == "watchPath"
+        || js_name == "unwatchPath"
     {
         return "filesystem";
     }
@@ -1110,7 +1154,12 @@ fn categorize_op(js_name: &str, is_async: bool) -> &'static str {
     }
 
     // Prompt operations
-    if js_name == "startPrompt" || js_name == "setPromptSuggestions" {
+    if js_name == "startPrompt"
+        || js_name == "setPromptSuggestions"
+        || js_name == "getPromptState"
+        || js_name == "closePrompt"
+        || js_name == "acceptPromptSuggestion"
+    {
         return "prompt";
     }
 
@@ -23,8 +23,8 @@ const editor = getEditor();
 const NAMESPACE = "buffer-modified";
 const PRIORITY = 5; // Lower than git-gutter (10) and diagnostics
 
-// Colors (RGB) - Blue to distinguish from git gutter (green/yellow/red)
-const COLOR = [100, 149, 237] as [number, number, number]; // Cornflower blue
+// Theme accent color, to distinguish from git gutter (diff colors)
+const COLOR = "accent";
 
 // Symbol
 const SYMBOL = "│";
@@ -88,10 +88,11 @@ function markLinesModified(bufferId: number, startLine: number, endLine: number)
       line,
       NAMESPACE,
       SYMBOL,
-      COLOR[0],
-      COLOR[1],
-      COLOR[2],
-      PRIORITY
+      0,
+      0,
+      0,
+      PRIORITY,
+      COLOR
     );
   }
 }
@@ -119,10 +120,11 @@ function reapplyIndicatorsFromDiff(bufferId: number): void {
         line,
         NAMESPACE,
         SYMBOL,
-        COLOR[0],
-        COLOR[1],
-        COLOR[2],
-        PRIORITY
+        0,
+        0,
+        0,
+        PRIORITY,
+        COLOR
       );
     }
   }
@@ -61,17 +61,33 @@ const state: DiagnosticsState = {
 // Color Definitions
 // =============================================================================
 
+// Theme color names, so the panel follows theme changes
 const colors = {
-  error: [255, 100, 100] as [number, number, number],
-  warning: [255, 200, 100] as [number, number, number],
-  info: [100, 200, 255] as [number, number, number],
-  hint: [150, 150, 150] as [number, number, number],
-  file: [180, 180, 255] as [number, number, number],
-  location: [150, 255, 150] as [number, number, number],
-  header: [255, 200, 100] as [number, number, number],
-  selected: [80, 80, 120] as [number, number, number],
+  error: "diagnostic.error",
+  warning: "diagnostic.warning",
+  info: "diagnostic.info",
+  hint: "diagnostic.hint",
+  file: "syntax.type",
+  location: "editor.line_number_fg",
+  header: "accent",
+  selected: "accent",
 };
 
+/** Color a range of the panel with a theme color */
+function highlight(
+  bufferId: number,
+  start: number,
+  end: number,
+  color: string,
+  underline: boolean,
+  bold: boolean
+): void {
+  editor.addOverlay(
+    bufferId, "diag", start, end, 0, 0, 0,
+    underline, bold, false, -1, -1, -1, false, color
+  );
+}
+
 // =============================================================================
 // Keybindings
 // =============================================================================
@@ -305,38 +321,22 @@ function applyHighlighting(): void {
 
     // Highlight current line if it's a diagnostic line (entire line gets background)
     if (isCurrentLine && isDiagnosticLine) {
-      editor.addOverlay(
-        bufferId, "diag", lineStart, lineEnd,
-        colors.selected[0], colors.selected[1], colors.selected[2],
-        true, true, false
-      );
+      highlight(bufferId, lineStart, lineEnd, colors.selected, true, true);
     }
 
     // Help line highlighting (dimmed)
     if (line.startsWith("Enter:")) {
-      editor.addOverlay(
-        bufferId, "diag", lineStart, lineEnd,
-        colors.hint[0], colors.hint[1], colors.hint[2],
-        false, true, false
-      );
+      highlight(bufferId, lineStart, lineEnd, colors.hint, false, true);
     }
 
     // Header highlighting
     if (line.startsWith("Diagnostics")) {
-      editor.addOverlay(
-        bufferId, "diag", lineStart, lineEnd,
-        colors.header[0], colors.header[1], colors.header[2],
-        true, true, false
-      );
+      highlight(bufferId, lineStart, lineEnd, colors.header, true, true);
     }
 
     // File header highlighting
     if (line.endsWith(":") && !line.startsWith("Diagnostics") && !line.startsWith(" ")) {
-      editor.addOverlay(
-        bufferId, "diag", lineStart, lineEnd,
-        colors.file[0], colors.file[1], colors.file[2],
-        false, true, false
-      );
+      highlight(bufferId, lineStart, lineEnd, colors.file, false, true);
     }
 
     // Severity icon highlighting
@@ -345,7 +345,7 @@ function applyHighlighting(): void {
       const iconStart = lineStart + line.indexOf("[");
       const iconEnd = iconStart + 3;
 
-      let color: [number, number, number];
+      let color: string;
       switch (iconMatch[1]) {
         case "E": color = colors.error; break;
         case "W": color = colors.warning; break;
@@ -354,22 +354,14 @@ function applyHighlighting(): void {
         default: color = colors.hint;
       }
 
-      editor.addOverlay(
-        bufferId, "diag", iconStart, iconEnd,
-        color[0], color[1], color[2],
-        false, true, false
-      );
+      highlight(bufferId, iconStart, iconEnd, color, false, true);
 
       // Location highlighting (line:col after icon)
       const locMatch = line.match(/\[.\]\s+(\d+:\d+)/);
       if (locMatch && locMatch.index !== undefined) {
         const locStart = lineStart + line.indexOf(locMatch[1]);
         const locEnd = locStart + locMatch[1].length;
-        editor.addOverlay(
-          bufferId, "diag", locStart, locEnd,
-          colors.location[0], colors.location[1], colors.location[2],
-          false, false, false
-        );
+        highlight(bufferId, locStart, locEnd, colors.location, false, false);
       }
     }
 
@@ -87,9 +87,10 @@ const blameState: BlameState = {
 // Color Definitions for Header Styling
 // =============================================================================
 
+// Theme color names, so headers follow theme changes
 const colors = {
-  headerFg: [0, 0, 0] as [number, number, number],           // Black text
-  headerBg: [200, 200, 200] as [number, number, number],     // Light gray background
+  headerFg: "editor.fg",
+  headerBg: "ui.inline_code_bg", // Gray band
 };
 
 // =============================================================================
@@ -382,15 +383,13 @@ function addBlameHeaders(): void {
       blameState.bufferId,
       block.startByte,        // anchor position
       headerText,             // text content
-      colors.headerFg[0],     // fg_r
-      colors.headerFg[1],     // fg_g
-      colors.headerFg[2],     // fg_b
-      colors.headerBg[0],     // bg_r
-      colors.headerBg[1],     // bg_g
-      colors.headerBg[2],     // bg_b
+      0, 0, 0,                // fg_r, fg_g, fg_b (theme color below)
+      -1, -1, -1,             // bg_r, bg_g, bg_b (theme color below)
       true,                   // above (LineAbove)
       BLAME_NAMESPACE,        // namespace for bulk removal
-      0                       // priority
+      0,                      // priority
+      colors.headerFg,        // fg color name
+      colors.headerBg         // bg color name
     );
   }
 
@@ -92,7 +92,8 @@ globalThis.onGitGrepPromptChanged = function(args: {
 
   // Spawn git grep asynchronously
   const cwd = editor.getCwd();
-  editor.spawnProcess("git", ["grep", "-n", "--column", "-I", "--", query], cwd)
+  const caseArgs = editor.isProjectSearchCaseSensitive(query, true) ? [] : ["-i"];
+  editor.spawnProcess("git", ["grep", "-n", "--column", "-I", ...caseArgs, "--", query], cwd)
     .then((result) => {
       if (result.exit_code === 0) {
         // Parse results and update suggestions
@@ -22,11 +22,11 @@ const editor = getEditor();
 const NAMESPACE = "git-gutter";
 const PRIORITY = 10; // Lower than diagnostics
 
-// Colors (RGB)
+// Theme colors, so the markers follow theme changes
 const COLORS = {
-  added: [80, 250, 123] as [number, number, number],    // Green
-  modified: [255, 184, 108] as [number, number, number], // Orange/Yellow
-  deleted: [255, 85, 85] as [number, number, number],    // Red
+  added: "diff.added",
+  modified: "diff.modified",
+  deleted: "diff.removed",
 };
 
 // Symbols
@@ -279,10 +279,11 @@ async function updateGitGutter(bufferId: number): Promise<void> {
           line,
           NAMESPACE,
           symbol,
-          color[0],
-          color[1],
-          color[2],
-          PRIORITY
+          0,
+          0,
+          0,
+          PRIORITY,
+          color
         );
       } else {
         // Added/modified indicators show on each affected line
@@ -294,10 +295,11 @@ async function updateGitGutter(bufferId: number): Promise<void> {
             line,
             NAMESPACE,
             symbol,
-            color[0],
-            color[1],
-            color[2],
-            PRIORITY
+            0,
+            0,
+            0,
+            PRIORITY,
+            color
           );
         }
       }
@@ -100,30 +100,48 @@ const fileViewState: GitFileViewState = {
 // Color Definitions (for syntax highlighting)
 // =============================================================================
 
+// Theme color names, so highlights follow theme changes
 const colors = {
-  hash: [255, 180, 50] as [number, number, number],       // Yellow/Orange
-  author: [100, 200, 255] as [number, number, number],    // Cyan
-  date: [150, 255, 150] as [number, number, number],      // Green
-  subject: [255, 255, 255] as [number, number, number],   // White
-  header: [255, 200, 100] as [number, number, number],    // Gold
-  separator: [100, 100, 100] as [number, number, number], // Gray
-  selected: [80, 80, 120] as [number, number, number],    // Selection background
-  diffAdd: [100, 255, 100] as [number, number, number],   // Green for additions
-  diffDel: [255, 100, 100] as [number, number, number],   // Red for deletions
-  diffHunk: [150, 150, 255] as [number, number, number],  // Blue for hunk headers
-  branch: [255, 150, 255] as [number, number, number],    // Magenta for branches
-  tag: [255, 255, 100] as [number, number, number],       // Yellow for tags
-  remote: [255, 130, 100] as [number, number, number],    // Orange for remotes
-  graph: [150, 150, 150] as [number, number, number],     // Gray for graph
+  hash: "syntax.constant",
+  author: "syntax.type",
+  date: "syntax.string",
+  subject: "editor.fg",
+  header: "accent",
+  separator: "editor.line_number_fg",
+  selected: "accent",
+  diffAdd: "diff.added",
+  diffDel: "diff.removed",
+  diffHunk: "diagnostic.info",
+  branch: "syntax.keyword",
+  tag: "diagnostic.warning",
+  remote: "diff.modified",
+  graph: "editor.line_number_fg",
   // Syntax highlighting colors
-  syntaxKeyword: [200, 120, 220] as [number, number, number],  // Purple for keywords
-  syntaxString: [180, 220, 140] as [number, number, number],   // Light green for strings
-  syntaxComment: [120, 120, 120] as [number, number, number],  // Gray for comments
-  syntaxNumber: [220, 180, 120] as [number, number, number],   // Orange for numbers
-  syntaxFunction: [100, 180, 255] as [number, number, number], // Blue for functions
-  syntaxType: [80, 200, 180] as [number, number, number],      // Teal for types
+  syntaxKeyword: "syntax.keyword",
+  syntaxString: "syntax.string",
+  syntaxComment: "syntax.comment",
+  syntaxNumber: "syntax.constant",
+  syntaxFunction: "syntax.function",
+  syntaxType: "syntax.type",
 };
 
+/** Color a range with a theme color */
+function addColorOverlay(
+  bufferId: number,
+  namespace: string,
+  start: number,
+  end: number,
+  color: string,
+  underline: boolean,
+  bold: boolean,
+  italic: boolean
+): void {
+  editor.addOverlay(
+    bufferId, namespace, start, end, 0, 0, 0,
+    underline, bold, italic, -1, -1, -1, false, color
+  );
+}
+
 // =============================================================================
 // Mode Definitions
 // =============================================================================
@@ -336,14 +354,12 @@ function applyGitLogHighlighting(): void {
 
     // Highlight section header
     if (line === editor.t("panel.commits_header")) {
-      editor.addOverlay(
+      addColorOverlay(
         bufferId,
         "gitlog",
         lineStart,
         lineEnd,
-        colors.header[0],
-        colors.header[1],
-        colors.header[2],
+        colors.header,
         true,  // underline
         true,  // bold
         false  // italic
@@ -364,14 +380,12 @@ function applyGitLogHighlighting(): void {
 
     // Highlight entire line if cursor is on it (using selected color with underline)
     if (isCurrentLine) {
-      editor.addOverlay(
+      addColorOverlay(
         bufferId,
         "gitlog",
         lineStart,
         lineEnd,
-        colors.selected[0],
-        colors.selected[1],
-        colors.selected[2],
+        colors.selected,
         true,  // underline to make it visible
         true,  // bold
         false  // italic
@@ -381,14 +395,12 @@ function applyGitLogHighlighting(): void {
     // Parse the line format: "shortHash (author, relativeDate) subject [refs]"
     // Highlight hash (first 7+ chars until space)
     const hashEnd = commit.shortHash.length;
-    editor.addOverlay(
+    addColorOverlay(
       bufferId,
       "gitlog",
       lineStart,
       lineStart + hashEnd,
-      colors.hash[0],
-      colors.hash[1],
-      colors.hash[2],
+      colors.hash,
       false, // underline
       false, // bold
       false  // italic
@@ -400,14 +412,12 @@ function applyGitLogHighlighting(): void {
     if (authorStartInLine >= 0) {
       const authorStart = lineStart + authorStartInLine + 1; // skip "("
       const authorEnd = authorStart + commit.author.length;
-      editor.addOverlay(
+      addColorOverlay(
         bufferId,
         "gitlog",
         authorStart,
         authorEnd,
-        colors.author[0],
-        colors.author[1],
-        colors.author[2],
+        colors.author,
         false, // underline
         false, // bold
         false  // italic
@@ -420,14 +430,12 @@ function applyGitLogHighlighting(): void {
     if (dateStartInLine >= 0) {
       const dateStart = lineStart + dateStartInLine + 2; // skip ", "
       const dateEnd = dateStart + commit.relativeDate.length;
-      editor.addOverlay(
+      addColorOverlay(
         bufferId,
         "gitlog",
         dateStart,
         dateEnd,
-        colors.date[0],
-        colors.date[1],
-        colors.date[2],
+        colors.date,
         false, // underline
         false, // bold
         false  // italic
@@ -449,14 +457,12 @@ function applyGitLogHighlighting(): void {
           refColor = colors.remote;
         }
 
-        editor.addOverlay(
+        addColorOverlay(
           bufferId,
           "gitlog",
           refsStart,
           refsEnd,
-          refColor[0],
-          refColor[1],
-          refColor[2],
+          refColor,
           false, // underline
           true,  // bold (make refs stand out)
           false  // italic
@@ -621,14 +627,12 @@ function applyCommitDetailHighlighting(): void {
 
     // Highlight diff additions (green)
     if (line.startsWith("+") && !line.startsWith("+++")) {
-      editor.addOverlay(
+      addColorOverlay(
         bufferId,
         "gitdetail",
         lineStart,
         lineEnd,
-        colors.diffAdd[0],
-        colors.diffAdd[1],
-        colors.diffAdd[2],
+        colors.diffAdd,
         false, // underline
         false, // bold
         false  // italic
@@ -636,14 +640,12 @@ function applyCommitDetailHighlighting(): void {
     }
     // Highlight diff deletions (red)
     else if (line.startsWith("-") && !line.startsWith("---")) {
-      editor.addOverlay(
+      addColorOverlay(
         bufferId,
         "gitdetail",
         lineStart,
         lineEnd,
-        colors.diffDel[0],
-        colors.diffDel[1],
-        colors.diffDel[2],
+        colors.diffDel,
         false, // underline
         false, // bold
         false  // italic
@@ -651,14 +653,12 @@ function applyCommitDetailHighlighting(): void {
     }
     // Highlight hunk headers (cyan/blue)
     else if (line.startsWith("@@")) {
-      editor.addOverlay(
+      addColorOverlay(
         bufferId,
         "gitdetail",
         lineStart,
         lineEnd,
-        colors.diffHunk[0],
-        colors.diffHunk[1],
-        colors.diffHunk[2],
+        colors.diffHunk,
         false, // underline
         true,  // bold
         false  // italic
@@ -669,14 +669,12 @@ function applyCommitDetailHighlighting(): void {
       const hashMatch = line.match(/^commit ([a-f0-9]+)/);
       if (hashMatch) {
         const hashStart = lineStart + 7; // "commit " is 7 chars
-        editor.addOverlay(
+        addColorOverlay(
           bufferId,
           "gitdetail",
           hashStart,
           hashStart + hashMatch[1].length,
-          colors.hash[0],
-          colors.hash[1],
-          colors.hash[2],
+          colors.hash,
           false, // underline
           true,  // bold
           false  // italic
@@ -685,14 +683,12 @@ function applyCommitDetailHighlighting(): void {
     }
     // Highlight author line
     else if (line.startsWith("Author:")) {
-      editor.addOverlay(
+      addColorOverlay(
         bufferId,
         "gitdetail",
         lineStart + 8, // "Author: " is 8 chars
         lineEnd,
-        colors.author[0],
-        colors.author[1],
-        colors.author[2],
+        colors.author,
         false, // underline
         false, // bold
         false  // italic
@@ -700,14 +696,12 @@ function applyCommitDetailHighlighting(): void {
     }
     // Highlight date line
     else if (line.startsWith("Date:")) {
-      editor.addOverlay(
+      addColorOverlay(
         bufferId,
         "gitdetail",
         lineStart + 6, // "Date: " is 6 chars (with trailing spaces it's 8)
         lineEnd,
-        colors.date[0],
-        colors.date[1],
-        colors.date[2],
+        colors.date,
         false, // underline
         false, // bold
         false  // italic
@@ -715,14 +709,12 @@ function applyCommitDetailHighlighting(): void {
     }
     // Highlight diff file headers
     else if (line.startsWith("diff --git")) {
-      editor.addOverlay(
+      addColorOverlay(
         bufferId,
         "gitdetail",
         lineStart,
         lineEnd,
-        colors.header[0],
-        colors.header[1],
-        colors.header[2],
+        colors.header,
         false, // underline
         true,  // bold
         false  // italic
@@ -1087,7 +1079,7 @@ function applyFileViewHighlighting(bufferId: number, content: string, filePath:
         inMultilineComment = true;
       }
       if (inMultilineComment) {
-        editor.addOverlay(bufferId, "syntax", lineStart, lineStart + line.length, colors.syntaxComment[0], colors.syntaxComment[1], colors.syntaxComment[2], false, false, true);
+        addColorOverlay(bufferId, "syntax", lineStart, lineStart + line.length, colors.syntaxComment, false, false, true);
         if (line.includes("*/")) {
           inMultilineComment = false;
         }
@@ -1106,7 +1098,7 @@ function applyFileViewHighlighting(bufferId: number, content: string, filePath:
       }
     }
     if (inMultilineString) {
-      editor.addOverlay(bufferId, "syntax", lineStart, lineStart + line.length, colors.syntaxString[0], colors.syntaxString[1], colors.syntaxString[2], false, false, false);
+      addColorOverlay(bufferId, "syntax", lineStart, lineStart + line.length, colors.syntaxString, false, false, false);
       byteOffset += line.length + 1;
       continue;
     }
@@ -1120,7 +1112,7 @@ function applyFileViewHighlighting(bufferId: number, content: string, filePath:
     }
 
     if (commentStart >= 0) {
-      editor.addOverlay(bufferId, "syntax", lineStart + commentStart, lineStart + line.length, colors.syntaxComment[0], colors.syntaxComment[1], colors.syntaxComment[2], false, false, true);
+      addColorOverlay(bufferId, "syntax", lineStart + commentStart, lineStart + line.length, colors.syntaxComment, false, false, true);
     }
 
     // String highlighting (simple: find "..." and '...')
@@ -1139,7 +1131,7 @@ function applyFileViewHighlighting(bufferId: number, content: string, filePath:
         if (i < line.length) i++; // Include closing quote
         const end = i;
         if (commentStart < 0 || start < commentStart) {
-          editor.addOverlay(bufferId, "syntax", lineStart + start, lineStart + end, colors.syntaxString[0], colors.syntaxString[1], colors.syntaxString[2], false, false, false);
+          addColorOverlay(bufferId, "syntax", lineStart + start, lineStart + end, colors.syntaxString, false, false, false);
         }
       } else {
         i++;
@@ -1155,7 +1147,7 @@ function applyFileViewHighlighting(bufferId: number, content: string, filePath:
         const kwEnd = kwStart + keyword.length;
         // Don't highlight if inside comment
         if (commentStart < 0 || kwStart < commentStart) {
-          editor.addOverlay(bufferId, "syntax", lineStart + kwStart, lineStart + kwEnd, colors.syntaxKeyword[0], colors.syntaxKeyword[1], colors.syntaxKeyword[2], false, true, false);
+          addColorOverlay(bufferId, "syntax", lineStart + kwStart, lineStart + kwEnd, colors.syntaxKeyword, false, true, false);
         }
       }
     }
@@ -1167,7 +1159,7 @@ function applyFileViewHighlighting(bufferId: number, content: string, filePath:
       const numStart = numMatch.index;
       const numEnd = numStart + numMatch[0].length;
       if (commentStart < 0 || numStart < commentStart) {
-        editor.addOverlay(bufferId, "syntax", lineStart + numStart, lineStart + numEnd, colors.syntaxNumber[0], colors.syntaxNumber[1], colors.syntaxNumber[2], false, false, false);
+        addColorOverlay(bufferId, "syntax", lineStart + numStart, lineStart + numEnd, colors.syntaxNumber, false, false, false);
       }
     }
 
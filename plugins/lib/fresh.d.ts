@@ -91,6 +91,76 @@ interface ProcessHandle extends PromiseLike<SpawnResult> {
   kill(): Promise<boolean>;
 }
 
+/** Handle for a progress entry started with startProgress */
+interface ProgressHandle {
+  /** The progress ID */
+  readonly id: number;
+  /** Set the percentage (a number, 0-100) or the message (a string) */
+  update(percentOrMessage: number | string): boolean;
+  /** Remove the entry. Returns false if it was already finished */
+  finish(): boolean;
+  /** Call the named globalThis function when the user cancels */
+  onCancel(handlerName: string): boolean;
+}
+
+/**
+ * An event and the handlers to call with it
+ *
+ * The dispatcher takes it with op_fresh_take_event, so the data is never
+ * spliced into the dispatching script.
+ */
+interface PendingEvent {
+  handlers: EventHandlerInfo[];
+  data: unknown;
+}
+
+/** Replacement of a byte range of a buffer */
+interface TsBufferEdit {
+  /** Start byte offset (inclusive) */
+  start: number;
+  /** End byte offset (exclusive) */
+  end: number;
+  /** Text replacing the range */
+  text: string;
+}
+
+/** Options for registerExplorerAction */
+interface TsExplorerActionOptions {
+  /** Id of the action, e.g. "open_terminal_here" */
+  id: string;
+  /** Text of the menu entry */
+  label: string;
+  /** Entries it is offered on: "file", "dir" or "any" (default "any") */
+  when?: string | null;
+  handler: string;
+}
+
+/** A parameter of a registered command */
+interface TsCommandArg {
+  /** Key of the value in the action's `options.args` */
+  name: string;
+  /** "string", "number", "path" or "choice" */
+  type: string;
+  /** Values offered for a "choice" parameter */
+  choices?: string[] | null;
+  /** Whether the parameter may be left out (default false) */
+  optional?: boolean | null;
+}
+
+/** Options for registerCommand */
+interface TsCommandOptions {
+  /**
+   * The action honors `options.dry_run` by reporting what it would do
+   * instead of doing it
+   */
+  dry_run?: boolean | null;
+  /**
+   * Parameters collected before the action runs, passed by name in
+   * `options.args`
+   */
+  args?: TsCommandArg[] | null;
+}
+
 /** Result from spawnProcess */
 interface SpawnResult {
   /** Complete stdout as string. Newlines preserved; trailing newline included. */
@@ -107,6 +177,14 @@ interface BackgroundProcessResult {
   process_id: number;
 }
 
+/** A registered event handler */
+interface EventHandlerInfo {
+  /** Name of the globalThis function called with the event data */
+  handler: string;
+  /** Plugin that registered the handler */
+  plugin: string;
+}
+
 /** File stat information */
 interface FileStat {
   /** Whether the path exists */
@@ -131,6 +209,12 @@ interface BufferInfo {
   modified: boolean;
   /** Buffer length in bytes */
   length: number;
+  /** Whether the buffer's file can be written */
+  writable: boolean;
+  /** Why the file can't be written ("permissions" or "read_only_filesystem"), null if it can */
+  read_only_reason?: string | null;
+  /** Whether the buffer is pinned, so bulk closes leave it open */
+  pinned: boolean;
 }
 
 /** Diff vs last save for a buffer */
@@ -182,6 +266,36 @@ interface TsDiagnostic {
   range: TsDiagnosticRange;
 }
 
+/** Diagnostic counts of one file */
+interface FileDiagnosticCounts {
+  /** Canonical file URI, the same for every spelling a server used */
+  uri: string;
+  /** Local path of the file (null for URIs that don't name a local file) */
+  path?: string | null;
+  /** Number of errors */
+  errors: number;
+  /** Number of warnings */
+  warnings: number;
+  /** Number of information diagnostics */
+  infos: number;
+  /** Number of hints, including diagnostics without a severity */
+  hints: number;
+}
+
+/** Diagnostic counts of the workspace and of each file */
+interface DiagnosticsSummary {
+  /** Number of errors in all files */
+  errors: number;
+  /** Number of warnings in all files */
+  warnings: number;
+  /** Number of information diagnostics in all files */
+  infos: number;
+  /** Number of hints in all files */
+  hints: number;
+  /** Files with diagnostics, the ones with the most errors then warnings first */
+  files: FileDiagnosticCounts[];
+}
+
 /** Viewport information */
 interface ViewportInfo {
   /** Byte offset of the top-left visible position */
@@ -194,6 +308,18 @@ interface ViewportInfo {
   height: number;
 }
 
+/** Terminal size and capabilities */
+interface TerminalInfo {
+  /** Terminal width in cells */
+  width: number;
+  /** Terminal height in cells */
+  height: number;
+  /** Whether 24-bit color is available */
+  true_color: boolean;
+  /** Width of East Asian ambiguous characters: "narrow" or "wide" */
+  unicode_width_ambiguous: string;
+}
+
 /** Suggestion for prompt autocomplete */
 interface PromptSuggestion {
   /** Display text for the suggestion */
@@ -208,6 +334,48 @@ interface PromptSuggestion {
   keybinding?: string | null;
 }
 
+/** State of the active prompt */
+interface PromptState {
+  /** Whether a prompt is currently open */
+  active: boolean;
+  /** Type identifier of a plugin prompt (null for built-in prompts or when inactive) */
+  prompt_type?: string | null;
+  /** Label displayed before the input */
+  label: string;
+  /** Text typed so far */
+  text: string;
+  /** Cursor position in the input (byte offset) */
+  cursor: number;
+  /** Index of the highlighted suggestion (null if none) */
+  selected_suggestion_index?: number | null;
+}
+
+/** State of the file explorer tree */
+interface FileExplorerState {
+  /** Whether the explorer panel is shown */
+  visible: boolean;
+  /** Directory at the root of the tree */
+  root: string;
+  /** Selected file or directory (null if nothing is selected) */
+  selected_path?: string | null;
+  /** Whether the selection is a directory */
+  selected_is_dir: boolean;
+  /** Expanded directories, including the root, sorted */
+  expanded_paths: string[];
+}
+
+/** A recently used project directory */
+interface RecentProject {
+  /** Absolute path of the project directory */
+  path: string;
+  /** When the project was last opened, in Unix seconds */
+  last_opened: number;
+  /** Whether the user pinned the project */
+  pinned: boolean;
+  /** Whether the directory still exists */
+  exists: boolean;
+}
+
 /** Directory entry from readDir */
 interface DirEntry {
   /** Entry name only (not full path). Join with parent path to get absolute path. */
@@ -218,6 +386,24 @@ interface DirEntry {
   is_dir: boolean;
 }
 
+/** File found by compareDirectories */
+interface DirCompareFile {
+  /** Path relative to both directories, with `/` separators */
+  path: string;
+  /** "only-left", "only-right", "different" or "identical" */
+  status: string;
+}
+
+/** Result of compareDirectories */
+interface DirCompareResult {
+  /** Left directory, resolved */
+  left: string;
+  /** Right directory, resolved */
+  right: string;
+  /** Files of both trees, sorted by path */
+  files: DirCompareFile[];
+}
+
 /** Entry for virtual buffer content with embedded metadata */
 interface TextPropertyEntry {
   /** Text to display. Include trailing newline for separate lines. */
@@ -254,8 +440,14 @@ interface CreateVirtualBufferOptions {
   show_cursors?: boolean | null;
   /** Disable all editing commands (default: false) */
   editing_disabled?: boolean | null;
+  /** Show ANSI color codes in the entries as colors (default: false) */
+  parse_ansi?: boolean | null;
   /** Enable/disable line wrapping (None = use global setting) */
   line_wrap?: boolean | null;
+  /** Recreate the panel when the session is restored (requires panel_id, default: false) */
+  persistent?: boolean | null;
+  /** Owning plugin; filled in by the plugin's editor object */
+  plugin?: string | null;
 }
 
 /** Options for creating a virtual buffer in an existing split */
@@ -276,6 +468,8 @@ interface CreateVirtualBufferInExistingSplitOptions {
   show_cursors?: boolean | null;
   /** Whether editing is disabled for this buffer (default false) */
   editing_disabled?: boolean | null;
+  /** Show ANSI color codes in the entries as colors (default: false) */
+  parse_ansi?: boolean | null;
   /** Enable/disable line wrapping (None = use global setting) */
   line_wrap?: boolean | null;
 }
@@ -296,6 +490,26 @@ interface CreateVirtualBufferInCurrentSplitOptions {
   show_cursors?: boolean | null;
   /** Whether editing is disabled for this buffer (default false) */
   editing_disabled?: boolean | null;
+  /** Show ANSI color codes in the entries as colors (default: false) */
+  parse_ansi?: boolean | null;
+}
+
+/** Options for createBuffer */
+interface CreateBufferOptions {
+  /** Initial text; the buffer starts modified unless it's empty */
+  content?: string | null;
+  /** Language for highlighting, a key of the `languages` config (e.g., "rust") */
+  language?: string | null;
+  /** Name shown in the tab and buffer switcher; without `language`, its extension picks the highlighting */
+  name_hint?: string | null;
+}
+
+/** Options for createBufferFromFile */
+interface CreateBufferFromFileOptions {
+  /** Language for highlighting, a key of the `languages` config (default: from the template's name) */
+  language?: string | null;
+  /** Name shown in the tab and buffer switcher (default: the template's file name) */
+  name_hint?: string | null;
 }
 
 /** JavaScript representation of ActionSpec (with optional count) */
@@ -304,6 +518,64 @@ interface ActionSpecJs {
   count?: number | null;
 }
 
+/** TypeScript struct for the outcome of an action executed by a plugin */
+interface ActionResult {
+  /** Whether the action exists and ran without error */
+  ok: boolean;
+  /** Why the action failed (unknown action name or execution error) */
+  error?: string | null;
+  /** Whether the active buffer or its primary cursor position changed */
+  cursor_moved: boolean;
+  /** Whether the content of the buffer that was active changed */
+  buffer_changed: boolean;
+}
+
+/** TypeScript struct for a binding that loses to another binding of the same keys */
+interface ShadowedBinding {
+  /** Action or command name */
+  action: string;
+  /** Context the binding was defined for ("global", "normal", ...) */
+  context: string;
+  /** Where it comes from: "keymap", "user" or "mode" */
+  source: string;
+  /** Keymap name, for "keymap" bindings */
+  keymap?: string | null;
+  /** Mode name, for "mode" bindings */
+  mode?: string | null;
+  /** Plugin that defined the mode */
+  plugin?: string | null;
+}
+
+/** TypeScript struct for one key of the effective keymap */
+interface KeymapBinding {
+  /** Key or chord as displayed (e.g. "Ctrl+S", "G G") */
+  key: string;
+  /** Action or command name */
+  action: string;
+  /** Human-readable description of the action */
+  description: string;
+  /** Context the binding was defined for ("global", "normal", ...) */
+  context: string;
+  /** Where it comes from: "keymap", "user" or "mode" */
+  source: string;
+  /** Keymap name, for "keymap" bindings */
+  keymap?: string | null;
+  /** Mode name, for "mode" bindings */
+  mode?: string | null;
+  /** Plugin that defined the mode */
+  plugin?: string | null;
+  /** Bindings of the same key that this one wins over, highest priority first */
+  shadowed: ShadowedBinding[];
+}
+
+/** TypeScript struct for a position in a buffer */
+interface LineCol {
+  /** Line (0-indexed) */
+  line: number;
+  /** Byte offset from the start of the line */
+  column: number;
+}
+
 /** TypeScript struct for action popup action */
 interface TsActionPopupAction {
   id: string;
@@ -318,6 +590,67 @@ interface TsActionPopupOptions {
   actions: TsActionPopupAction[];
 }
 
+/** TypeScript struct for anchored popup options */
+interface TsAnchoredPopupOptions {
+  /** Buffer the popup points into */
+  buffer_id: number;
+  /** Byte offset the popup is anchored to */
+  position: number;
+  /** Text lines, wrapped to max_width */
+  lines: string[];
+  /** Widest the popup may be, in columns (default 60) */
+  max_width?: number | null;
+  /** Close the popup after this long (default: when the cursor or mouse moves away) */
+  timeout_ms?: number | null;
+}
+
+/** TypeScript struct for notification options */
+interface TsNotifyOptions {
+  id?: string | null;
+  message: string;
+  severity?: string | null;
+  timeout_ms?: number | null;
+  actions?: TsActionPopupAction[] | null;
+}
+
+/** TypeScript struct for the byte range of a pending edit */
+interface TsPendingEditRange {
+  start: number;
+  end: number;
+}
+
+/** TypeScript struct for a proposed edit in a pending edits review */
+interface TsPendingEdit {
+  /** File path (absolute, or relative to the working directory) */
+  file: string;
+  /** Byte range in the file that will be replaced */
+  range: TsPendingEditRange;
+  /** Text currently in the range; edits whose text no longer matches are skipped */
+  old_text: string;
+  /** Replacement text */
+  new_text: string;
+  /** Whether the edit starts checked (default: true) */
+  enabled?: boolean | null;
+}
+
+/** TypeScript struct for pending edits review options */
+interface TsReviewPendingEditsOptions {
+  /** Title shown in the review header */
+  title: string;
+  /** Proposed edits */
+  edits: TsPendingEdit[];
+  /** Apply the selected edits when the user confirms (default: true) */
+  apply?: boolean | null;
+}
+
+/** Options for progressStart */
+interface TsProgressOptions {
+  /** What is in progress, e.g. "Indexing workspace" */
+  title: string;
+  /** Whether the user may cancel it (default false) */
+  cancellable?: boolean | null;
+}
+
 /**
  * Main editor API interface
  */
@@ -359,6 +692,27 @@ interface EditorAPI {
    * @returns User configuration object (sparse - only explicitly set values)
    */
   getUserConfig(): unknown;
+  /**
+   * Whether a project-wide search for a pattern should match case
+   *
+   * Follows `search.project_smart_case`: when on (the default), only patterns
+   * with an uppercase letter match case; regex escapes such as `\S` don't
+   * count. When off, searches always match case.
+   * @param pattern - The search pattern
+   * @param is_regex - Whether the pattern is a regular expression
+   * @returns true to search case-sensitively
+   */
+  isProjectSearchCaseSensitive(pattern: string, is_regex: boolean): boolean;
+  /**
+   * Get the colors of the active theme
+   *
+   * Keys are the names decorations accept instead of RGB values, such as
+   * "diagnostic.error" or "editor.bg"; colors the theme leaves to the
+   * terminal are missing. Prefer passing names to decorations, which follow
+   * theme changes, over the numbers returned here.
+   * @returns Object mapping color names to [r, g, b]
+   */
+  getThemeColors(): Record<string, unknown>;
   /**
    * Get the buffer ID of the focused editor pane
    *
@@ -410,7 +764,8 @@ interface EditorAPI {
   /**
    * Get the line number of the primary cursor (1-indexed)
    *
-   * Line numbers start at 1. Returns 1 if no cursor exists.
+   * Line numbers start at 1. Returns 1 if no cursor exists. In buffers too
+   * large to have a line index the number is estimated.
    * For byte offset use getCursorPosition instead.
    */
   getCursorLine(): number;
@@ -437,16 +792,66 @@ interface EditorAPI {
    * @returns Array of Diagnostic objects with file URI, severity, message, and range
    */
   getAllDiagnostics(): TsDiagnostic[];
+  /**
+   * Get the diagnostic counts of the workspace and of each file
+   *
+   * Every spelling of a file's URI counts towards one entry. Cheaper than
+   * getAllDiagnostics when only the numbers are needed.
+   * @returns DiagnosticsSummary with totals and per-file counts
+   */
+  getDiagnosticsSummary(): DiagnosticsSummary;
+  /**
+   * Get terminal size and capabilities
+   *
+   * Uses the same detection and `editor.ambiguous_width` setting as the renderer,
+   * so plugins can align virtual-buffer layouts with what is actually drawn.
+   * Subscribe to the "terminal_resized" hook to re-layout when the size changes.
+   * @returns TerminalInfo object
+   */
+  getTerminalInfo(): TerminalInfo;
+  /**
+   * Get the file explorer root, selection and expanded directories
+   *
+   * Commands registered for the "fileexplorer" context can use this to read
+   * the entry they act on. Reflects the editor state as of the last update.
+   * @returns FileExplorerState object, or null if the explorer has never been opened
+   */
+  getFileExplorerState(): FileExplorerState | null;
+  /**
+   * Check whether input to a split is locked
+   * @param split_id - ID of the split
+   * @returns true if edits typed in the split are refused
+   */
+  isSplitLocked(split_id: number): boolean;
+  /**
+   * Get the effective keymap with the source of every binding
+   *
+   * Lists what each key does in the given context after merging the active
+   * keymap, the user's keybindings and, for "normal" and "fileExplorer", the
+   * editor mode and the active buffer's mode, so a plugin can build its own
+   * cheat sheet. Terminal key equivalents added automatically are not listed.
+   * @param context - Context name as in `when` clauses ("normal", "prompt", ...), or null for the current one
+   * @returns Array of KeymapBinding; throws if the context name is unknown
+   * @example
+   * const bindings = await editor.getKeymap("normal");
+   * const conflicts = bindings.filter(b => b.shadowed.length > 0);
+   */
+  getKeymap(context: string): Promise<KeymapBinding[]>;
   /**
    * Get text from a buffer range
    *
    * Used by vi mode plugin for yank operations - reads text without deleting.
+   * Rejects ranges longer than the `editor.plugin_text_limit_bytes` setting
+   * (16 MiB by default); read large buffers with readBufferChunks.
+   * Bytes of the file that aren't valid UTF-8 read as the private use
+   * character U+10FD00 + byte (U+10FD80..U+10FDFF, 4 bytes each); writing
+   * them back keeps the original byte on save.
    * @param buffer_id - Buffer ID
    * @param start - Start byte offset
    * @param end - End byte offset
-   * @returns Text content of the range, or empty string on error
+   * @returns Text content of the range
    */
-  getBufferText(buffer_id: number, start: number, end: number): Promise<string>;
+  getBufferText(buffer_id: number, start: number, end: number, plugin_name: string): Promise<string>;
   /**
    * Get the current global editor mode
    *
@@ -496,6 +901,33 @@ interface EditorAPI {
    * @returns true if suggestions were set successfully
    */
   setPromptSuggestions(suggestions: PromptSuggestion[]): boolean;
+  /**
+   * Get the state of the active prompt
+   *
+   * Reads what the user has typed so far, outside of the prompt hooks.
+   * Reflects the editor state as of the last update, like other state queries.
+   * @returns PromptState object (active is false when no prompt is open)
+   */
+  getPromptState(): PromptState;
+  /**
+   * Close the active plugin prompt, firing the "prompt_cancelled" hook
+   *
+   * Only closes a plugin prompt whose type matches, so a stale close never
+   * cancels a prompt the user opened in the meantime. Built-in prompts are
+   * never closed.
+   * @param prompt_type - Type identifier to match (any plugin prompt if omitted)
+   * @returns true if the request was sent successfully
+   */
+  closePrompt(prompt_type?: string | null): boolean;
+  /**
+   * Submit the active plugin prompt as if the user selected a suggestion
+   *
+   * Fires "prompt_confirmed" with the given selected_index. Ignored if no
+   * plugin prompt is open or the index is out of range.
+   * @param index - Index into the current suggestions
+   * @returns true if the request was sent successfully
+   */
+  acceptPromptSuggestion(index: number): boolean;
 
   // === Buffer Mutations ===
   /**
@@ -509,9 +941,9 @@ interface EditorAPI {
   /**
    * Reload configuration from file
    *
-   * After a plugin saves config changes to the config file, call this to reload
-   * the editor's in-memory configuration. This ensures the editor and plugins
-   * stay in sync with the saved config.
+   * Call this after the config file was changed outside the editor to reload
+   * the editor's in-memory configuration. To change a setting, use
+   * `setConfigValue` instead of writing the config file.
    */
   reloadConfig(): void;
   /**
@@ -568,6 +1000,37 @@ interface EditorAPI {
    * @param end - End byte offset (exclusive)
    */
   deleteRange(buffer_id: number, start: number, end: number): boolean;
+  /**
+   * Replace several byte ranges of a buffer at once
+   *
+   * Offsets refer to the buffer before any edit applies, so edits don't
+   * shift each other. Ranges must not overlap; if any is invalid none are
+   * applied. All edits are a single undo step, which undo and redo name
+   * `label` in the status bar if given.
+   * Operation is asynchronous; returns true if command was sent successfully.
+   * @param buffer_id - Target buffer ID
+   * @param edits - Ranges to replace and their new text
+   * @param label - Name of the undo step, e.g. "Sort imports"
+   * @example
+   * // Remove a trailing semicolon on the first two lines
+   * editor.applyEdits(bufferId, [{ start: 9, end: 10, text: "" }, { start: 20, end: 21, text: "" }], "Remove semicolons");
+   */
+  applyEdits(buffer_id: number, edits: TsBufferEdit[], label?: string | null): boolean;
+  /**
+   * Finish a save action started by a "save_action:<name>" event
+   *
+   * Call this once the handler's edits are sent; the next save action then
+   * runs. Passing an error reports the step as failed.
+   * @param request_id - The request_id of the save_action event
+   * @param error - Why the step failed, or null if it succeeded
+   * @example
+   * globalThis.addLicense = (args) => {
+   * editor.insertText(args.buffer_id, 0, "// SPDX-License-Identifier: MIT\n");
+   * editor.finishSaveAction(args.request_id, null);
+   * };
+   * editor.on("save_action:license", "addLicense");
+   */
+  finishSaveAction(request_id: number, error?: string | null): boolean;
   /**
    * Clear all overlays in a namespace
    * @param buffer_id - The buffer ID
@@ -575,6 +1038,16 @@ interface EditorAPI {
    * @returns true if successful
    */
   clearNamespace(buffer_id: number, namespace: string): boolean;
+  /**
+   * Keep a namespace's overlays when a buffer is reverted
+   *
+   * "Revert Buffer to Saved" clears the overlays of every namespace not marked
+   * stable, since their positions refer to the replaced text.
+   * @param namespace - The overlay namespace
+   * @param stable - true to keep its overlays across reverts, false to clear them
+   * @returns true if the command was sent
+   */
+  setRevertStableNamespace(namespace: string, stable: boolean): boolean;
   /**
    * Enable/disable line numbers for a buffer
    * @param buffer_id - The buffer ID
@@ -582,6 +1055,23 @@ interface EditorAPI {
    * @returns true if successful
    */
   setLineNumbers(buffer_id: number, enabled: boolean): boolean;
+  /**
+   * Let the editor close a buffer created by this plugin once no split has
+   * shown it and no plugin has used it for `editor.plugin_buffer_idle_close_secs`
+   * (the default for such buffers), or keep it while hidden
+   * @param buffer_id - The buffer ID
+   * @param enabled - false to keep a buffer that is hidden on purpose
+   * @returns true if the command was sent
+   */
+  setBufferIdleClose(buffer_id: number, enabled: boolean): boolean;
+  /**
+   * Pin a buffer so bulk close commands and automatic closing leave it open,
+   * or unpin it
+   * @param buffer_id - The buffer ID
+   * @param pinned - Whether the buffer is pinned
+   * @returns true if the command was sent
+   */
+  setBufferPinned(buffer_id: number, pinned: boolean): boolean;
   /**
    * Add a virtual line above or below a source line
    * @param buffer_id - The buffer ID
@@ -596,11 +1086,14 @@ interface EditorAPI {
    * @param above - Whether to insert above (true) or below (false) the line
    * @param namespace - Namespace for bulk removal (e.g., "git-blame")
    * @param priority - Priority for ordering multiple lines at same position
+   * @param fg_color_name - Theme color used instead of fg_r, fg_g, fg_b, empty for none
+   * @param bg_color_name - Theme color used instead of bg_r, bg_g, bg_b, empty for none
    * @returns true if virtual line was added
    */
-  addVirtualLine(buffer_id: number, position: number, text: string, fg_r: number, fg_g: number, fg_b: number, bg_r: i16, bg_g: i16, bg_b: i16, above: boolean, namespace: string, priority: number): boolean;
+  addVirtualLine(buffer_id: number, position: number, text: string, fg_r: number, fg_g: number, fg_b: number, bg_r: i16, bg_g: i16, bg_b: i16, above: boolean, namespace: string, priority: number, fg_color_name: string, bg_color_name: string): boolean;
   /**
-   * Set a line indicator in the gutter's indicator column
+   * Set a line indicator in the gutter's indicator column, or in a column
+   * added with registerGutterColumn
    * @param buffer_id - The buffer ID
    * @param line - Line number (0-indexed)
    * @param namespace - Namespace for grouping (e.g., "git-gutter", "breakpoints")
@@ -609,9 +1102,11 @@ interface EditorAPI {
    * @param g - Green color component (0-255)
    * @param b - Blue color component (0-255)
    * @param priority - Priority for display when multiple indicators exist (higher wins)
+   * @param color_name - Theme color used instead of r, g, b (e.g. "diff.added"), empty for none
+   * @param column - Name of a registered gutter column, empty for the indicator column
    * @returns true if indicator was set
    */
-  setLineIndicator(buffer_id: number, line: number, namespace: string, symbol: string, r: number, g: number, b: number, priority: number): boolean;
+  setLineIndicator(buffer_id: number, line: number, namespace: string, symbol: string, r: number, g: number, b: number, priority: number, color_name: string, column: string): boolean;
   /**
    * Clear all line indicators for a specific namespace
    * @param buffer_id - The buffer ID
@@ -619,6 +1114,53 @@ interface EditorAPI {
    * @returns true if indicators were cleared
    */
   clearLineIndicators(buffer_id: number, namespace: string): boolean;
+  /**
+   * Add a named column to the gutter of every buffer
+   *
+   * The column shows the line indicators set for it through setLineIndicator's
+   * `column` argument, so they don't compete with other plugins' indicators
+   * for the one-cell indicator column. Users can move, resize or hide it with
+   * the `editor.gutter_columns` and `editor.gutter_column_widths` settings.
+   * The column goes away when the plugin is unloaded.
+   * Throws if another plugin owns the name or the width or alignment is invalid.
+   * @param name - Column name, e.g. "coverage"
+   * @param width - Width in cells (1-8)
+   * @param alignment - "left", "center" or "right"
+   * @returns true if the column was added
+   * @example
+   * editor.registerGutterColumn("coverage", 2, "left");
+   * editor.setLineIndicator(bufferId, 9, "coverage", "▌", 0, 0, 0, 0, "diff.added", "coverage");
+   */
+  registerGutterColumn(name: string, width: number, alignment: string, plugin_name: string): boolean;
+  /**
+   * Remove a gutter column added with registerGutterColumn
+   * @param name - Column name
+   * @returns true if the plugin had registered the column
+   */
+  unregisterGutterColumn(name: string, plugin_name: string): boolean;
+  /**
+   * Add an entry to the file explorer's context menu
+   *
+   * Plugin entries show below the built-in ones, in registration order, on the
+   * entries `when` names. Choosing one calls the handler with the path of the
+   * file or directory the menu was opened on. Registering the same id again
+   * changes the entry. The entry goes away when the plugin is unloaded.
+   * Throws if another plugin owns the id or `when` is invalid.
+   * @param options - Id, label, entries it is offered on and handler name
+   * @returns true if the entry was added
+   * @example
+   * globalThis.onTerminalHere = (target) => {
+   * editor.setStatus(`terminal in ${target.path}`);
+   * };
+   * editor.registerExplorerAction({ id: "terminal_here", label: "Open Terminal Here", when: "dir", handler: "onTerminalHere" });
+   */
+  registerExplorerAction(options: TsExplorerActionOptions, plugin_name: string): boolean;
+  /**
+   * Remove an entry added with registerExplorerAction
+   * @param id - Id of the action
+   * @returns true if the plugin had registered the action
+   */
+  unregisterExplorerAction(id: string, plugin_name: string): boolean;
   /**
    * Submit a transformed view stream for a viewport
    * @param buffer_id - Buffer to apply the transform to
@@ -644,8 +1186,18 @@ interface EditorAPI {
   insertAtCursor(text: string): boolean;
   /** Translate a string for a plugin using the current locale */
   pluginTranslate(plugin_name: string, key: string, args: Record<string, unknown>): string;
-  /** Register a custom command that can be triggered by keybindings or the command palette */
-  registerCommand(name: string, description: string, action: string, contexts: string, source: string): boolean;
+  /**
+   * Register a custom command that can be triggered by keybindings or the command palette
+   * Plugins pass `{ dry_run: true }` after the contexts when the action honors
+   * `options.dry_run` by reporting what it would do instead of doing it.
+   * Declared `args` are prompted for when the command is run from the
+   * palette, and checked when given by a `run_command` key binding or as
+   * `:Name value…` in the palette. Throws if a parameter type is invalid.
+   * @example
+   * globalThis.greet = (options) => editor.setStatus(`Hello ${options.args.who}`);
+   * editor.registerCommand("Greet", "Greet someone", "greet", "", { args: [{ name: "who", type: "string" }] });
+   */
+  registerCommand(name: string, description: string, action: string, contexts: string, source: string, options: TsCommandOptions): boolean;
   /**
    * Unregister a custom command by name
    * @param name - The name of the command to unregister
@@ -663,7 +1215,11 @@ interface EditorAPI {
   setContext(name: string, active: boolean): boolean;
   /**
    * Open a file in the editor, optionally at a specific location
-   * @param path - File path to open
+   *
+   * `file://` URIs (as returned by language servers) are accepted and
+   * percent-decoded. Throws for other URIs, such as `untitled:` or `https:`,
+   * which don't name a local file.
+   * @param path - File path or file:// URI to open
    * @param line - Line number to jump to (0 for no jump)
    * @param column - Column number to jump to (0 for no jump)
    * @returns true if file was opened
@@ -671,8 +1227,10 @@ interface EditorAPI {
   openFile(path: string, line: number, column: number): boolean;
   /**
    * Open a file in a specific split pane
+   *
+   * Accepts `file://` URIs and throws for other URIs, like openFile.
    * @param split_id - The split ID to open the file in
-   * @param path - File path to open
+   * @param path - File path or file:// URI to open
    * @param line - Line number to jump to (0 for no jump)
    * @param column - Column number to jump to (0 for no jump)
    * @returns true if file was opened
@@ -722,6 +1280,8 @@ interface EditorAPI {
    * await editor.delay(100);  // Wait 100ms
    */
   delay(#[bigint] ms: number): Promise<[]>;
+  /** Take the event being emitted (internal, used by the event dispatcher) */
+  takeEvent(): PendingEvent | null;
   /** Find a buffer ID by its file path */
   findBufferByPath(path: string): number;
   /**
@@ -732,9 +1292,55 @@ interface EditorAPI {
    * @returns true if prompt was started successfully
    */
   startPromptWithInitial(label: string, prompt_type: string, initial_value: string): boolean;
+  /**
+   * Expand the parent directories of a path in the file explorer and select it
+   *
+   * Same as the explorer following the active file, but works while the
+   * explorer is hidden. Paths outside the explorer root are ignored. Fires
+   * "file_explorer_selection_changed" once the entry is selected.
+   * @param path - File or directory, relative to the working directory unless absolute
+   * @returns true if the request was sent successfully
+   */
+  revealInFileExplorer(path: string): boolean;
+  /**
+   * Show a different directory at the root of the file explorer
+   *
+   * The working directory, LSP servers and plugins stay as they are. The path
+   * is checked like a project switch: anything but an existing directory is
+   * rejected with an error in the status bar.
+   * @param path - Directory, relative to the working directory unless absolute
+   * @returns true if the request was sent successfully
+   */
+  setFileExplorerRoot(path: string): boolean;
+  /**
+   * Create an ordinary untitled buffer in the active split
+   *
+   * The buffer is like one the user creates with New File: it has no path,
+   * so saving asks for one, and it starts modified unless it's empty.
+   * @param options - Initial content, language and name
+   * @returns Promise resolving to the buffer ID; rejects for an unknown language
+   * @example
+   * const id = await editor.createBuffer({
+   * content: "fn main() {\n}\n",
+   * language: "rust",
+   * name_hint: "main.rs"
+   * });
+   */
+  createBuffer(options?: CreateBufferOptions | null): Promise<number>;
+  /**
+   * Create an untitled buffer holding the text of a template file
+   *
+   * The buffer isn't bound to the template: saving asks for a path.
+   * @param path - Template file (absolute or relative to cwd)
+   * @param options - Language and name, both taken from the template's file name by default
+   * @returns Promise resolving to the buffer ID; rejects if the file can't be read
+   * @example
+   * const id = await editor.createBufferFromFile("templates/component.tsx");
+   */
+  createBufferFromFile(path: string, options?: CreateBufferFromFileOptions | null): Promise<number>;
   /**
    * Send an arbitrary LSP request and receive the raw JSON response
-   * @param language - Language ID (e.g., "cpp")
+   * @param language - Language ID (e.g., "cpp"), or "language/name" for one of a language's several servers
    * @param method - Full LSP method (e.g., "textDocument/switchSourceHeader")
    * @param params - Optional request payload
    * @returns Promise resolving to the JSON response value
@@ -754,6 +1360,32 @@ interface EditorAPI {
    * @returns true if the ratio was set successfully
    */
   setSplitRatio(split_id: number, ratio: number): boolean;
+  /**
+   * Lock or unlock input to a split
+   *
+   * While a split is locked, edits typed with it focused are refused; edits
+   * made through the plugin API still apply. Useful for review panes.
+   * @param split_id - ID of the split
+   * @param locked - true to lock, false to unlock
+   * @returns true if the command was sent successfully
+   */
+  setSplitLocked(split_id: number, locked: boolean): boolean;
+  /**
+   * Show text in a split's winbar, the line at the top of the split
+   *
+   * Setting a segment again with the same id replaces it; empty text removes
+   * it. Segments are drawn flush right in the order they were first set.
+   * @param split_id - ID of the split
+   * @param id - Identifies the segment among the split's segments
+   * @param text - Text to show, empty to remove the segment
+   * @param priority - Segments with a lower priority are dropped first when the winbar is too narrow
+   * @param r - Red color component (0-255), -1 for the winbar's own color
+   * @param g - Green color component (0-255), -1 for the winbar's own color
+   * @param b - Blue color component (0-255), -1 for the winbar's own color
+   * @param color_name - Theme color used instead of r, g, b (e.g. "diagnostic.warning_fg"), empty for none
+   * @returns true if the command was sent successfully
+   */
+  setWinbarSegment(split_id: number, id: string, text: string, priority: number, r: i16, g: i16, b: i16, color_name: string): boolean;
   /**
    * Distribute all visible splits evenly
    * This adjusts the ratios of all container splits so each leaf split gets equal space
@@ -767,6 +1399,16 @@ interface EditorAPI {
    * @returns true if the command was sent successfully
    */
   setBufferCursor(buffer_id: number, position: number): boolean;
+  /**
+   * Go to a byte offset in a buffer, making it the active buffer
+   *
+   * The offset is clamped to the buffer and the cursor lands on the character
+   * boundary at or before it, with the view centered on it.
+   * @param buffer_id - ID of the buffer
+   * @param offset - Byte offset, e.g. from `grep --byte-offset`
+   * @returns true if the offset is valid and the command was sent
+   */
+  gotoByte(buffer_id: number, offset: number): boolean;
   /**
    * Execute a built-in editor action by name
    *
@@ -791,6 +1433,74 @@ interface EditorAPI {
    * @returns true if actions were sent successfully
    */
   executeActions(actions: ActionSpecJs[]): boolean;
+  /**
+   * Execute a built-in editor action by name and wait for its outcome
+   *
+   * Unlike executeAction, resolves only after the editor has applied the action,
+   * so a plugin can check the result before deciding what to do next (e.g. vi mode
+   * cancels a pending operator when its motion didn't move the cursor).
+   * @param action_name - Action name (e.g., "move_word_right", "move_line_end")
+   * @returns ActionResult with ok, error (if any), cursor_moved and buffer_changed
+   * @example
+   * const result = await editor.executeActionAwait("move_word_right");
+   * if (result.ok && result.cursor_moved) {
+   * // apply the operator to the range the motion covered
+   * }
+   */
+  executeActionAwait(action_name: string): Promise<ActionResult>;
+  /**
+   * Execute multiple actions in sequence and wait for each one's outcome
+   *
+   * Stops at the first action that fails, so the returned array is shorter than
+   * `actions` when one of them fails; its last entry is the failing action.
+   * @param actions - Array of {action: string, count?: number} objects
+   * @returns Array of ActionResult, one per action that was run
+   */
+  executeActionsAwait(actions: ActionSpecJs[]): Promise<ActionResult[]>;
+  /**
+   * Convert a byte offset to a line and column
+   *
+   * Uses the buffer's line index, so it costs the same anywhere in a file.
+   * Lines are 0-indexed and end at "\n"; the column counts bytes.
+   * @param buffer_id - Buffer ID
+   * @param byte - Byte offset, up to the buffer length
+   * @returns The LineCol of the byte; throws if the offset is past the end or the buffer is too large to have a line index
+   * @example
+   * const { line, column } = await editor.byteToLineCol(bufferId, editor.getCursorPosition());
+   */
+  byteToLineCol(buffer_id: number, byte: number): Promise<LineCol>;
+  /**
+   * Convert a line and column to a byte offset
+   *
+   * Uses the buffer's line index, so it costs the same anywhere in a file.
+   * @param buffer_id - Buffer ID
+   * @param line - Line (0-indexed)
+   * @param column - Byte offset in the line, clamped to the end of the line (before its "\n")
+   * @returns Byte offset; throws if the line doesn't exist or the buffer is too large to have a line index
+   * @example
+   * const start = await editor.lineColToByte(bufferId, 41, 0);
+   */
+  lineColToByte(buffer_id: number, line: number, column: number): Promise<number>;
+  /**
+   * Read a whole buffer in pieces of at most `chunk_size` bytes
+   *
+   * The handler is called with `{read_id, buffer_id, offset, text, done}` for
+   * each piece, in order, spread over editor ticks so a large buffer is never
+   * copied at once. Pieces end on character boundaries; the last one has
+   * `done` set. Closing the buffer ends the read early with an empty last piece.
+   * @param buffer_id - Buffer ID
+   * @param chunk_size - Most bytes in one piece (capped at `editor.plugin_text_limit_bytes`)
+   * @param handler_name - Name of globalThis function to call with each piece
+   * @returns Read ID, as passed to the handler
+   * @example
+   * let text = "";
+   * globalThis.onChunk = (chunk) => {
+   * text += chunk.text;
+   * if (chunk.done) editor.setStatus(`${text.length} characters`);
+   * };
+   * editor.readBufferChunks(editor.getActiveBufferId(), 1 << 20, "onChunk");
+   */
+  readBufferChunks(buffer_id: number, chunk_size: number, handler_name: string, plugin_name: string): number;
   /**
    * Set the global editor mode (for modal editing like vi mode)
    *
@@ -801,6 +1511,18 @@ interface EditorAPI {
    * @returns true if command was sent successfully
    */
   setEditorMode(mode?: string | null): boolean;
+  /**
+   * Set the terminal cursor style
+   *
+   * Overrides the `ui.cursor` config until cleared, for plugin modes the
+   * config doesn't cover. Pass null/undefined to go back to the configured
+   * styles.
+   *
+   * @param style - "default", "blinking_block", "steady_block", "blinking_bar",
+   * "steady_bar", "blinking_underline", "steady_underline", or null to clear
+   * @returns true if the style is valid and the command was sent
+   */
+  setCursorStyle(style?: string | null): boolean;
   /**
    * Show an action popup with buttons for user interaction
    *
@@ -808,14 +1530,69 @@ interface EditorAPI {
    * @param options - Popup configuration with id, title, message, and actions
    */
   showActionPopup(options: TsActionPopupOptions): boolean;
+  /**
+   * Show a small borderless popup anchored to a byte position
+   *
+   * It goes below the position's line, or above it near the bottom of the
+   * screen, and closes when the cursor or mouse leaves the word there, after
+   * timeout_ms, or with closeAnchoredPopup. Only one is shown at a time:
+   * a new one replaces the previous one, from any plugin. Not shown while the
+   * LSP hover popup is open.
+   * @param options - buffer_id, position, lines, optional max_width and timeout_ms
+   * @example
+   * editor.on("mouse_moved_in_buffer", "onMouseMoved");
+   * globalThis.onMouseMoved = (data) => {
+   * editor.showAnchoredPopup({ buffer_id: data.buffer_id, position: data.byte_offset, lines: ["#ff0000"] });
+   * };
+   */
+  showAnchoredPopup(options: TsAnchoredPopupOptions): boolean;
+  /** Close the popup shown with showAnchoredPopup */
+  closeAnchoredPopup(): boolean;
+  /**
+   * Show a toast notification above the status bar
+   *
+   * Notifications stack (newest at the bottom) and go away after their
+   * timeout. Choosing a button, or a notification with buttons going away
+   * unanswered, fires the ActionPopupResult hook with the notification id.
+   * @param options - Message, severity ("info", "warning", "error"), timeout_ms (0 = until dismissed), optional id and actions
+   * @returns The notification id
+   */
+  notify(options: TsNotifyOptions): string;
+  /**
+   * Open the pending edits review buffer for a set of proposed edits
+   *
+   * Lists every edit grouped by file. The user toggles edits (Space) or whole
+   * files (f), expands context (Tab), then confirms (Enter) or cancels (q).
+   * Unless `apply` is false, confirmed edits are applied atomically per buffer.
+   * @param options - Review title, edits, and whether to apply on confirm
+   * @returns The edits the user kept, or null if the review was cancelled
+   * @example
+   * const kept = await editor.reviewPendingEdits({
+   * title: "Rename foo to bar",
+   * edits: [{ file: "src/main.rs", range: { start: 10, end: 13 }, old_text: "foo", new_text: "bar", enabled: true }]
+   * });
+   */
+  reviewPendingEdits(options: TsReviewPendingEditsOptions): Promise<TsPendingEdit[] | null>;
+  /**
+   * Set a single config value and persist it to the user config file
+   *
+   * Plugins should use this rather than writing the config file themselves: the
+   * editor writes atomically, keeps a backup, and asks the user before
+   * overwriting changes made to the file since it was loaded.
+   * @param path - Dot-separated key path (e.g., "editor.tab_size", "lsp.rust.enabled")
+   * @param value - New value for the key
+   * @returns true if the request was sent
+   */
+  setConfigValue(path: string, value: unknown): boolean;
   /**
    * Disable LSP for a specific language and persist to config
    *
    * This is used by LSP helper plugins to let users disable LSP for languages
    * where the server is not available or not working.
    * @param language - The language to disable LSP for (e.g., "python", "rust")
+   * @param server - Name of the one server to disable when the language has several, or null for all
    */
-  disableLspForLanguage(language: string): boolean;
+  disableLspForLanguage(language: string, server?: string | null): boolean;
   /**
    * Create a scroll sync group for anchor-based synchronized scrolling
    *
@@ -832,6 +1609,50 @@ interface EditorAPI {
   setScrollSyncAnchors(group_id: number, anchors: Vec<(usize, usize): boolean;
   /** Remove a scroll sync group */
   removeScrollSyncGroup(group_id: number): boolean;
+  /**
+   * Compare two open buffers side by side, as "Compare Buffer With…" does
+   *
+   * The left buffer is made active and the right one shown in a new split.
+   * The diff follows edits to either buffer until one of them is closed.
+   * @param left_buffer_id - Buffer shown on the left
+   * @param right_buffer_id - Buffer shown on the right
+   * @returns true if the command was sent
+   */
+  compareBuffers(left_buffer_id: number, right_buffer_id: number): boolean;
+  /**
+   * Show the progress of a long operation
+   *
+   * Prefer `startProgress`, which wraps these ops in a handle. A single entry
+   * shows in the status bar, several show as notifications. The entry starts
+   * with a spinner until a percentage is given, and is finished for the plugin
+   * with a warning if the plugin is unloaded or stops updating it for a minute.
+   * @param options - Title and whether the user may cancel it
+   * @returns Progress ID for progressUpdate, progressFinish and progressOnCancel
+   */
+  progressStart(options: TsProgressOptions, plugin_name: string): number;
+  /**
+   * Report progress: a number sets the percentage (0-100), a string the message
+   * @param progress_id - ID returned by progressStart
+   * @param percent_or_message - Percentage or message
+   * @returns false if the progress was already finished
+   */
+  progressUpdate(progress_id: number, percent_or_message: unknown): boolean;
+  /**
+   * Remove a progress entry
+   * @param progress_id - ID returned by progressStart
+   * @returns false if it was already finished
+   */
+  progressFinish(progress_id: number): boolean;
+  /**
+   * Call a handler when the user cancels a progress entry
+   *
+   * The handler is called once with `{progress_id}`. The entry shows as
+   * cancelling until the plugin finishes it.
+   * @param progress_id - ID returned by progressStart
+   * @param handler_name - Name of globalThis function to call
+   * @returns false if the progress was already finished
+   */
+  progressOnCancel(progress_id: number, handler_name: string, plugin_name: string): boolean;
 
   /**
    * Spawn an external process and return a cancellable handle
@@ -851,6 +1672,24 @@ interface EditorAPI {
    * search.kill();  // Cancel the search
    */
   spawnProcess(command: string, args?: string[], cwd?: string | null): ProcessHandle;
+
+  /**
+   * Show the progress of a long operation and return a handle to report it
+   *
+   * With the handle, `update(50)` sets the percentage, `update("file.rs")` the
+   * message, `onCancel("handlerName")` names the function called when the user
+   * cancels, and `finish()` removes the entry. Until a percentage is given a
+   * spinner shows. Entries left unfinished are finished with a warning when the
+   * plugin unloads or stops updating them for a minute.
+   * @param options - Title and whether the user may cancel it
+   * @example
+   * const progress = await editor.startProgress({ title: "Indexing", cancellable: true });
+   * globalThis.onIndexCancelled = () => { cancelled = true; };
+   * progress.onCancel("onIndexCancelled");
+   * progress.update(40);
+   * progress.finish();
+   */
+  startProgress(options: TsProgressOptions): Promise<ProgressHandle>;
   // === Overlay Operations ===
   /**
    * Add a colored highlight overlay to text without modifying content
@@ -870,9 +1709,11 @@ interface EditorAPI {
    * @param bold - Use bold text
    * @param italic - Use italic text
    * @param extend_to_line_end - Extend background to end of visual line
+   * @param color_name - Theme color used instead of r, g, b (e.g. "diagnostic.error"), empty for none
+   * @param bg_color_name - Theme color used instead of bg_r, bg_g, bg_b, empty for none
    * @returns true if overlay was added
    */
-  addOverlay(buffer_id: number, namespace: string, start: number, end: number, r: number, g: number, b: number, bg_r: i16, bg_g: i16, bg_b: i16, underline: boolean, bold: boolean, italic: boolean, extend_to_line_end: boolean): boolean;
+  addOverlay(buffer_id: number, namespace: string, start: number, end: number, r: number, g: number, b: number, bg_r: i16, bg_g: i16, bg_b: i16, underline: boolean, bold: boolean, italic: boolean, extend_to_line_end: boolean, color_name: string, bg_color_name: string): boolean;
   /**
    * Remove a specific overlay by its handle
    * @param buffer_id - The buffer ID
@@ -905,9 +1746,10 @@ interface EditorAPI {
    * @param b - Blue color component (0-255)
    * @param before - Whether to insert before (true) or after (false) the position
    * @param use_bg - Whether to use the color as background (true) or foreground (false)
+   * @param color_name - Theme color used instead of r, g, b, empty for none
    * @returns true if virtual text was added
    */
-  addVirtualText(buffer_id: number, virtual_text_id: string, position: number, text: string, r: number, g: number, b: number, before: boolean, use_bg: boolean): boolean;
+  addVirtualText(buffer_id: number, virtual_text_id: string, position: number, text: string, r: number, g: number, b: number, before: boolean, use_bg: boolean, color_name: string): boolean;
   /**
    * Remove virtual text by ID
    * @param buffer_id - The buffer ID
@@ -990,6 +1832,44 @@ interface EditorAPI {
    * }
    */
   readDir(path: string): DirEntry[];
+  /**
+   * Compare two directory trees file by file
+   *
+   * Walks both trees, skipping hidden and ignored files like the editor's
+   * "Compare Directories" command, and compares the files found on both
+   * sides by size, then by content. Throws if either path is not a directory.
+   * @param left - Directory path (absolute or relative to cwd)
+   * @param right - Directory path (absolute or relative to cwd)
+   * @example
+   * const result = await editor.compareDirectories("out.orig", "out");
+   * const changed = result.files.filter(f => f.status !== "identical");
+   */
+  compareDirectories(left: string, right: string): Promise<DirCompareResult>;
+  /**
+   * Watch a file or glob for changes on disk
+   *
+   * The handler is called with `{watch_id, path, kind}` where kind is
+   * "created", "modified" or "removed". Rapid successive writes to a file
+   * produce one event. Relative paths are taken from the working directory.
+   * Globs such as "logs/*.txt" are matched under their leading directory, with
+   * "**" matching any number of directories.
+   * Throws if the plugin already has the maximum number of watches.
+   * @param path_or_glob - File path or glob to watch
+   * @param handler_name - Name of globalThis function to call with each change
+   * @returns Watch ID for unwatchPath
+   * @example
+   * globalThis.onTagsChanged = (change) => {
+   * editor.setStatus(`${change.path} ${change.kind}`);
+   * };
+   * const watchId = editor.watchPath("tags", "onTagsChanged");
+   */
+  watchPath(path_or_glob: string, handler_name: string, plugin_name: string): number;
+  /**
+   * Stop a watch started with watchPath
+   * @param watch_id - ID returned by watchPath
+   * @returns true if the watch existed
+   */
+  unwatchPath(watch_id: number): boolean;
 
   // === Environment Operations ===
   /**
@@ -1007,6 +1887,15 @@ interface EditorAPI {
    * which is important for test isolation.
    */
   getCwd(): string;
+  /**
+   * Get the recently used project directories
+   *
+   * The list "Open Recent Project" offers: pinned projects first, then the
+   * rest, each most recently opened first. Read-only; the editor records
+   * projects as they are opened.
+   * @returns Array of RecentProject objects
+   */
+  getRecentProjects(): RecentProject[];
 
   // === Path Operations ===
   /**
@@ -1067,7 +1956,9 @@ interface EditorAPI {
    * Subscribe to an editor event
    *
    * Handler must be a global function name (not a closure).
-   * Multiple handlers can be registered for the same event.
+   * Multiple handlers can be registered for the same event; registering the
+   * same handler twice has no further effect. Handlers are removed when their
+   * plugin is unloaded or reloaded.
    * Events: "buffer_save", "cursor_moved", "buffer_modified", etc.
    * @param event_name - Event to subscribe to
    * @param handler_name - Name of globalThis function to call with event data
@@ -1077,20 +1968,20 @@ interface EditorAPI {
    * };
    * editor.on("buffer_save", "onSave");
    */
-  on(event_name: string, handler_name: string): boolean;
+  on(event_name: string, handler_name: string, plugin_name: string): boolean;
   /**
    * Unregister an event handler
    * @param event_name - Name of the event
    * @param handler_name - Name of the handler to remove
    * @returns true if handler was found and removed
    */
-  off(event_name: string, handler_name: string): boolean;
+  off(event_name: string, handler_name: string, plugin_name: string): boolean;
   /**
    * Get list of registered handlers for an event
    * @param event_name - Name of the event
-   * @returns Array of handler function names
+   * @returns Array of handlers with the plugin that registered each
    */
-  getHandlers(event_name: string): string[];
+  getHandlers(event_name: string): EventHandlerInfo[];
 
   // === Virtual Buffer Operations ===
   /**
@@ -1099,6 +1990,9 @@ interface EditorAPI {
    * Use for results panels, diagnostics, logs, etc. The panel_id enables
    * idempotent updates: if a panel with that ID exists, its content is replaced
    * instead of creating a new split. Define the mode with defineMode first.
+   * Panels created with `persistent: true` are recreated as placeholders when
+   * the session is restored, and the "panel_restore" hook asks the plugin to
+   * fill them again.
    * @param options - Buffer configuration
    * @example
    * // First define the mode with keybindings
@@ -1136,6 +2030,10 @@ interface EditorAPI {
   createVirtualBuffer(options: CreateVirtualBufferInCurrentSplitOptions): Promise<number>;
   /**
    * Define a buffer mode with keybindings
+   *
+   * A mode's bindings sit below popups, prompts and completion in the input
+   * focus stack: while one of those is open it gets the keys, and the mode
+   * only sees keys that reach normal editing.
    * @param name - Mode name (e.g., "diagnostics-list")
    * @param parent - Parent mode name for inheritance (e.g., "special"), or null
    * @param bindings - Array of [key_string, command_name] pairs
@@ -1197,6 +2095,16 @@ interface EditorAPI {
    * @returns true if content was set successfully
    */
   setVirtualBufferContent(buffer_id: number, entries: TextPropertyEntry[]): boolean;
+  /**
+   * Add entries at the end of a virtual buffer's content
+   *
+   * Lets a panel with many entries send them in chunks, showing the first
+   * ones while the rest are still being collected.
+   * @param buffer_id - ID of the virtual buffer
+   * @param entries - Array of text entries with properties
+   * @returns true if the entries were sent successfully
+   */
+  appendVirtualBufferContent(buffer_id: number, entries: TextPropertyEntry[]): boolean;
 
 }
 
@@ -235,7 +235,7 @@ async function runSearch(query: string): Promise<void> {
       "--column",
       "--no-heading",
       "--color=never",
-      "--smart-case",
+      editor.isProjectSearchCaseSensitive(query, true) ? "--case-sensitive" : "--ignore-case",
       "--max-count=100",
       "-g", "!.git",
       "-g", "!node_modules",
@@ -101,31 +101,37 @@ const mergeState: MergeState = {
 // Color Definitions
 // =============================================================================
 
+// Theme color names, so highlights follow theme changes
 const colors = {
   // Panel headers
-  oursHeader: [100, 200, 255] as [number, number, number],    // Cyan for OURS
-  theirsHeader: [255, 180, 100] as [number, number, number],  // Orange for THEIRS
-  resultHeader: [150, 255, 150] as [number, number, number],  // Green for RESULT
+  oursHeader: "diagnostic.info",
+  theirsHeader: "diff.modified",
+  resultHeader: "diff.added",
 
   // Conflict highlighting
-  conflictOurs: [50, 80, 100] as [number, number, number],    // Blue-tinted background
-  conflictTheirs: [100, 70, 50] as [number, number, number],  // Orange-tinted background
-  conflictBase: [70, 70, 70] as [number, number, number],     // Gray for base
+  conflictOurs: "diagnostic.info",
+  conflictTheirs: "diff.modified",
+  conflictBase: "editor.line_number_fg",
 
   // Intra-line diff colors
-  diffAdd: [50, 100, 50] as [number, number, number],         // Green for additions
-  diffDel: [100, 50, 50] as [number, number, number],         // Red for deletions
-  diffMod: [50, 50, 100] as [number, number, number],         // Blue for modifications
+  diffAdd: "diff.added_bg",
+  diffDel: "diff.removed_bg",
+  diffMod: "diff.modified_bg",
 
   // Selection
-  selected: [80, 80, 120] as [number, number, number],        // Selection highlight
+  selected: "selection",
 
   // Buttons/actions
-  button: [100, 149, 237] as [number, number, number],        // Cornflower blue
-  resolved: [100, 200, 100] as [number, number, number],      // Green for resolved
-  unresolved: [200, 100, 100] as [number, number, number],    // Red for unresolved
+  button: "accent",
+  resolved: "diff.added",
+  unresolved: "diagnostic.error",
 };
 
+/** Underline a range with a theme color */
+function underline(bufferId: number, namespace: string, start: number, end: number, color: string): void {
+  editor.addOverlay(bufferId, namespace, start, end, 0, 0, 0, true, false, false, -1, -1, -1, false, color);
+}
+
 // =============================================================================
 // Mode Definition
 // =============================================================================
@@ -958,16 +964,7 @@ function highlightPanel(bufferId: number, side: "ours" | "theirs"): void {
 
     // Highlight conflict header lines
     if (line.includes("--- Conflict")) {
-      editor.addOverlay(
-        bufferId,
-        `merge-conflict-header-${lineIdx}`,
-        lineStart,
-        lineEnd,
-        conflictColor[0],
-        conflictColor[1],
-        conflictColor[2],
-        true // underline
-      );
+      underline(bufferId, `merge-conflict-header-${lineIdx}`, lineStart, lineEnd, conflictColor);
     }
 
     byteOffset = lineEnd + 1;
@@ -993,16 +990,7 @@ function highlightResultPanel(bufferId: number): void {
 
     // Highlight conflict markers
     if (line.startsWith("<<<<<<<") || line.startsWith("=======") || line.startsWith(">>>>>>>")) {
-      editor.addOverlay(
-        bufferId,
-        `merge-marker-${lineIdx}`,
-        lineStart,
-        lineEnd,
-        colors.unresolved[0],
-        colors.unresolved[1],
-        colors.unresolved[2],
-        true // underline
-      );
+      underline(bufferId, `merge-marker-${lineIdx}`, lineStart, lineEnd, colors.unresolved);
     }
 
     byteOffset = lineEnd + 1;
@@ -27,6 +27,12 @@ let searchResults: SearchResult[] = [];
 let searchPattern: string = "";
 let replaceText: string = "";
 let searchRegex: boolean = false;
+// Whether the search ignores case (see search.project_smart_case)
+let searchIgnoreCase: boolean = false;
+
+// Running git grep, killed if the user cancels its progress
+let runningSearch: ProcessHandle | null = null;
+let searchCancelled = false;
 
 // Maximum results to display
 const MAX_RESULTS = 200;
@@ -176,9 +182,13 @@ async function performSearch(pattern: string, replace: string, isRegex: boolean)
   searchPattern = pattern;
   replaceText = replace;
   searchRegex = isRegex;
+  searchIgnoreCase = !editor.isProjectSearchCaseSensitive(pattern, isRegex);
 
   // Build git grep args
   const args = ["grep", "-n", "--column", "-I"];
+  if (searchIgnoreCase) {
+    args.push("-i");
+  }
   if (isRegex) {
     args.push("-E"); // Extended regex
   } else {
@@ -186,12 +196,24 @@ async function performSearch(pattern: string, replace: string, isRegex: boolean)
   }
   args.push("--", pattern);
 
+  const progress = await editor.startProgress({
+    title: editor.t("progress.searching", { pattern }),
+    cancellable: true,
+  });
+  progress.onCancel("onSearchReplaceSearchCancelled");
+  searchCancelled = false;
+
   try {
     const cwd = editor.getCwd();
-    const result = await editor.spawnProcess("git", args, cwd);
+    runningSearch = editor.spawnProcess("git", args, cwd);
+    const result = await runningSearch;
 
     searchResults = [];
 
+    if (searchCancelled) {
+      editor.setStatus(editor.t("status.search_cancelled"));
+      return;
+    }
     if (result.exit_code === 0) {
       for (const line of result.stdout.split("\n")) {
         if (!line.trim()) continue;
@@ -211,9 +233,18 @@ async function performSearch(pattern: string, replace: string, isRegex: boolean)
   } catch (e) {
     editor.setStatus(editor.t("status.search_error", { error: String(e) }));
     searchResults = [];
+  } finally {
+    runningSearch = null;
+    progress.finish();
   }
 }
 
+// The user cancelled the search from its progress
+globalThis.onSearchReplaceSearchCancelled = function(): void {
+  searchCancelled = true;
+  runningSearch?.kill();
+};
+
 // Show the search results panel
 async function showResultsPanel(): Promise<void> {
   if (panelOpen && resultsBufferId !== null) {
@@ -246,7 +277,57 @@ async function showResultsPanel(): Promise<void> {
   }
 }
 
-// Execute replacements
+/**
+ * Calculate UTF-8 byte length of a string manually since TextEncoder is not available
+ */
+function getByteLength(str: string): number {
+  let s = 0;
+  for (let i = 0; i < str.length; i++) {
+    const code = str.charCodeAt(i);
+    if (code <= 0x7f) s += 1;
+    else if (code <= 0x7ff) s += 2;
+    else if (code >= 0xd800 && code <= 0xdfff) {
+      s += 4; i++;
+    } else s += 3;
+  }
+  return s;
+}
+
+// Escape a literal pattern for use in a RegExp
+function escapeRegExp(text: string): string {
+  return text.replace(/[.*+?^${}()|[\]\\]/g, "\\$&");
+}
+
+// Find every match on a line as [index, matchedText, replacement]
+function findLineMatches(line: string): [number, string, string][] {
+  const matches: [number, string, string][] = [];
+  if (searchRegex || searchIgnoreCase) {
+    const source = searchRegex ? searchPattern : escapeRegExp(searchPattern);
+    const flags = searchIgnoreCase ? "i" : "";
+    const regex = new RegExp(source, "g" + flags);
+    let match: RegExpExecArray | null;
+    while ((match = regex.exec(line)) !== null) {
+      if (match[0].length === 0) {
+        regex.lastIndex++;
+        continue;
+      }
+      // Literal replacements are taken as typed, without `$` patterns
+      const replacement = searchRegex
+        ? match[0].replace(new RegExp(source, flags), replaceText)
+        : replaceText;
+      matches.push([match.index, match[0], replacement]);
+    }
+  } else {
+    let index = line.indexOf(searchPattern);
+    while (index !== -1) {
+      matches.push([index, searchPattern, replaceText]);
+      index = line.indexOf(searchPattern, index + searchPattern.length);
+    }
+  }
+  return matches;
+}
+
+// Build pending edits for the selected results and hand them to the review buffer
 async function executeReplacements(): Promise<void> {
   const selectedResults = searchResults.filter(r => r.selected);
 
@@ -264,61 +345,59 @@ async function executeReplacements(): Promise<void> {
     fileGroups.get(result.file)!.push(result);
   }
 
-  let filesModified = 0;
-  let replacementsCount = 0;
+  const edits: TsPendingEdit[] = [];
   const errors: string[] = [];
 
   for (const [filePath, results] of fileGroups) {
     try {
-      // Read file
       const content = await editor.readFile(filePath);
       const lines = content.split("\n");
 
-      // Sort results by line (descending) to avoid offset issues
-      const sortedResults = [...results].sort((a, b) => {
-        if (a.line !== b.line) return b.line - a.line;
-        return b.column - a.column;
-      });
+      // Byte offset of each line start
+      const lineOffsets: number[] = [];
+      let offset = 0;
+      for (const line of lines) {
+        lineOffsets.push(offset);
+        offset += getByteLength(line) + 1;
+      }
 
-      // Apply replacements
-      for (const result of sortedResults) {
+      const seenLines = new Set<number>();
+      for (const result of results) {
         const lineIndex = result.line - 1;
-        if (lineIndex >= 0 && lineIndex < lines.length) {
-          let line = lines[lineIndex];
-
-          if (searchRegex) {
-            // Regex replacement
-            const regex = new RegExp(searchPattern, "g");
-            lines[lineIndex] = line.replace(regex, replaceText);
-          } else {
-            // Simple string replacement (all occurrences in line)
-            lines[lineIndex] = line.split(searchPattern).join(replaceText);
-          }
-          replacementsCount++;
+        if (lineIndex < 0 || lineIndex >= lines.length || seenLines.has(lineIndex)) {
+          continue;
+        }
+        seenLines.add(lineIndex);
+
+        const line = lines[lineIndex];
+        for (const [index, matched, replacement] of findLineMatches(line)) {
+          const start = lineOffsets[lineIndex] + getByteLength(line.slice(0, index));
+          edits.push({
+            file: filePath,
+            range: { start, end: start + getByteLength(matched) },
+            old_text: matched,
+            new_text: replacement,
+            enabled: true,
+          });
         }
       }
-
-      // Write back
-      const newContent = lines.join("\n");
-      await editor.writeFile(filePath, newContent);
-      filesModified++;
-
     } catch (e) {
       const errorMessage = e instanceof Error ? e.message : String(e);
       errors.push(`${filePath}: ${errorMessage}`);
     }
   }
 
-  // Report results
   if (errors.length > 0) {
-    editor.setStatus(editor.t("status.replaced_with_errors", { files: String(filesModified), errors: String(errors.length) }));
     editor.debug(`Replacement errors: ${errors.join(", ")}`);
-  } else {
-    editor.setStatus(editor.t("status.replaced", { count: String(replacementsCount), files: String(filesModified) }));
   }
 
-  // Close panel after replacement
+  const title = editor.t("review.title", { search: searchPattern, replace: replaceText });
+
+  // Close the results panel; the review buffer takes over from here
   globalThis.search_replace_close();
+
+  // The editor applies the checked edits and reports the outcome itself
+  await editor.reviewPendingEdits({ title, edits });
 }
 
 // Start search/replace workflow
@@ -368,7 +447,9 @@ globalThis.onSearchReplaceReplaceConfirmed = async function(args: {
 
   // Perform search and show results
   await performSearch(searchPattern, replaceText, false);
-  await showResultsPanel();
+  if (!searchCancelled) {
+    await showResultsPanel();
+  }
 
   return true;
 };
@@ -204,6 +204,19 @@ const THEME_SECTIONS: ThemeSection[] = [
       { key: "operator", displayName: "Operator", description: "Operators (+, -, =, etc.)", section: "syntax" },
     ],
   },
+  {
+    name: "diff",
+    displayName: "Diff",
+    description: "Added, removed and modified lines in diffs and the gutter",
+    fields: [
+      { key: "added_fg", displayName: "Added Foreground", description: "Added lines and their gutter marker", section: "diff" },
+      { key: "added_bg", displayName: "Added Background", description: "Added line highlight background", section: "diff" },
+      { key: "removed_fg", displayName: "Removed Foreground", description: "Removed lines and their gutter marker", section: "diff" },
+      { key: "removed_bg", displayName: "Removed Background", description: "Removed line highlight background", section: "diff" },
+      { key: "modified_fg", displayName: "Modified Foreground", description: "Modified lines and their gutter marker", section: "diff" },
+      { key: "modified_bg", displayName: "Modified Background", description: "Modified line highlight background", section: "diff" },
+    ],
+  },
 ];
 
 // =============================================================================
@@ -1149,6 +1162,14 @@ function createDefaultTheme(): Record<string, unknown> {
       constant: [79, 193, 255],
       operator: [212, 212, 212],
     },
+    diff: {
+      added_fg: [80, 250, 123],
+      added_bg: [35, 70, 35],
+      removed_fg: [255, 85, 85],
+      removed_bg: [80, 35, 35],
+      modified_fg: [255, 184, 108],
+      modified_bg: [35, 45, 80],
+    },
   };
 }
 
@@ -8,7 +8,7 @@ interface HighlightConfig {
   enabled: boolean;
   keywords: Array<{
     word: string;
-    color: [number, number, number];
+    color: string; // Theme color name
   }>;
 }
 
@@ -16,11 +16,11 @@ interface HighlightConfig {
 const config: HighlightConfig = {
   enabled: false,  // Start disabled, use Enable or Toggle to activate
   keywords: [
-    { word: "TODO", color: [255, 200, 50] },     // Yellow
-    { word: "FIXME", color: [255, 100, 100] },   // Red
-    { word: "XXX", color: [255, 150, 50] },      // Orange
-    { word: "HACK", color: [200, 100, 255] },    // Purple
-    { word: "NOTE", color: [100, 200, 255] },    // Blue
+    { word: "TODO", color: "diagnostic.warning" },
+    { word: "FIXME", color: "diagnostic.error" },
+    { word: "XXX", color: "diff.modified" },
+    { word: "HACK", color: "syntax.keyword" },
+    { word: "NOTE", color: "diagnostic.info" },
   ],
 };
 
@@ -55,10 +55,17 @@ function highlightLine(
           NAMESPACE,
           absoluteStart,
           absoluteEnd,
-          keyword.color[0],
-          keyword.color[1],
-          keyword.color[2],
-          false  // background color, not underline
+          0,
+          0,
+          0,
+          false, // underline
+          false, // bold
+          false, // italic
+          -1,
+          -1,
+          -1,
+          false, // extend_to_line_end
+          keyword.color
         );
       }
 
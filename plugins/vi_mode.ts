@@ -212,6 +212,10 @@ const motionToSelection: Record<string, string> = {
   move_line_end: "select_line_end",
   move_document_start: "select_document_start",
   move_document_end: "select_document_end",
+  move_paragraph_forward: "select_paragraph_forward",
+  move_paragraph_backward: "select_paragraph_backward",
+  move_sentence_forward: "select_sentence_forward",
+  move_sentence_backward: "select_sentence_backward",
 };
 
 // Map (operator, motion) pairs to atomic Rust actions
@@ -225,6 +229,10 @@ const atomicOperatorActions: OperatorMotionMap = {
     move_word_left: "delete_word_backward",
     move_line_end: "delete_to_line_end",
     move_line_start: "delete_to_line_start",
+    move_paragraph_forward: "delete_paragraph_forward",
+    move_paragraph_backward: "delete_paragraph_backward",
+    move_sentence_forward: "delete_sentence_forward",
+    move_sentence_backward: "delete_sentence_backward",
   },
   y: {
     // Yank operators
@@ -379,6 +387,22 @@ globalThis.vi_doc_end = function (): void {
   editor.executeAction("move_document_end");
 };
 
+globalThis.vi_paragraph = function (): void {
+  executeWithCount("move_paragraph_forward");
+};
+
+globalThis.vi_paragraph_back = function (): void {
+  executeWithCount("move_paragraph_backward");
+};
+
+globalThis.vi_sentence = function (): void {
+  executeWithCount("move_sentence_forward");
+};
+
+globalThis.vi_sentence_back = function (): void {
+  executeWithCount("move_sentence_backward");
+};
+
 globalThis.vi_page_down = function (): void {
   executeWithCount("page_down");
 };
@@ -904,6 +928,22 @@ globalThis.vi_vis_word_back = function (): void {
   executeWithCount("select_word_left");
 };
 
+globalThis.vi_vis_paragraph = function (): void {
+  executeWithCount("select_paragraph_forward");
+};
+
+globalThis.vi_vis_paragraph_back = function (): void {
+  executeWithCount("select_paragraph_backward");
+};
+
+globalThis.vi_vis_sentence = function (): void {
+  executeWithCount("select_sentence_forward");
+};
+
+globalThis.vi_vis_sentence_back = function (): void {
+  executeWithCount("select_sentence_backward");
+};
+
 globalThis.vi_vis_word_end = function (): void {
   const count = consumeCount();
   for (let i = 0; i < count; i++) {
@@ -1261,6 +1301,36 @@ globalThis.vi_to_brace = async function (): Promise<void> { await applyTextObjec
 globalThis.vi_to_bracket = async function (): Promise<void> { await applyTextObject("["); };
 globalThis.vi_to_angle = async function (): Promise<void> { await applyTextObject("<"); };
 
+// Sentence and paragraph objects are found by the editor's own actions,
+// which take the blanks after them like "as" and "ap" do
+function applySelectionTextObject(selectAction: string): void {
+  const operator = state.pendingOperator;
+  if (!operator) {
+    switchMode("normal");
+    return;
+  }
+
+  editor.executeAction(selectAction);
+  switch (operator) {
+    case "d":
+      editor.executeAction("cut");
+      break;
+    case "c":
+      editor.executeAction("cut");
+      switchMode("insert");
+      return;
+    case "y":
+      state.lastYankWasLinewise = false;
+      editor.executeAction("copy");
+      editor.executeAction("move_left");
+      break;
+  }
+  switchMode("normal");
+}
+
+globalThis.vi_to_sentence = function (): void { applySelectionTextObject("select_sentence"); };
+globalThis.vi_to_paragraph = function (): void { applySelectionTextObject("select_paragraph"); };
+
 // Cancel text object mode
 globalThis.vi_to_cancel = function (): void {
   switchMode("normal");
@@ -1457,6 +1527,22 @@ globalThis.vi_op_matching_bracket = function (): void {
   handleMotionWithOperator("go_to_matching_bracket");
 };
 
+globalThis.vi_op_paragraph = function (): void {
+  handleMotionWithOperator("move_paragraph_forward");
+};
+
+globalThis.vi_op_paragraph_back = function (): void {
+  handleMotionWithOperator("move_paragraph_backward");
+};
+
+globalThis.vi_op_sentence = function (): void {
+  handleMotionWithOperator("move_sentence_forward");
+};
+
+globalThis.vi_op_sentence_back = function (): void {
+  handleMotionWithOperator("move_sentence_backward");
+};
+
 globalThis.vi_cancel = function (): void {
   switchMode("normal");
 };
@@ -1496,6 +1582,10 @@ editor.defineMode("vi-normal", null, [
   ["C-d", "vi_half_page_down"],
   ["C-u", "vi_half_page_up"],
   ["%", "vi_matching_bracket"],
+  ["}", "vi_paragraph"],
+  ["{", "vi_paragraph_back"],
+  [")", "vi_sentence"],
+  ["(", "vi_sentence_back"],
   ["z z", "vi_center_cursor"],
 
   // Search
@@ -1684,6 +1774,10 @@ editor.defineMode("vi-operator-pending", null, [
   ["g g", "vi_op_doc_start"],
   ["G", "vi_op_doc_end"],
   ["%", "vi_op_matching_bracket"],
+  ["}", "vi_op_paragraph"],
+  ["{", "vi_op_paragraph_back"],
+  [")", "vi_op_sentence"],
+  ["(", "vi_op_sentence_back"],
 
   // Text objects
   ["i", "vi_text_object_inner"],
@@ -1704,6 +1798,10 @@ editor.defineMode("vi-text-object", null, [
   ["w", "vi_to_word"],
   ["W", "vi_to_WORD"],
 
+  // Prose objects
+  ["s", "vi_to_sentence"],
+  ["p", "vi_to_paragraph"],
+
   // Quote objects
   ["\"", "vi_to_dquote"],
   ["'", "vi_to_squote"],
@@ -1751,6 +1849,10 @@ editor.defineMode("vi-visual", null, [
   ["^", "vi_vis_line_start"],
   ["g g", "vi_vis_doc_start"],
   ["G", "vi_vis_doc_end"],
+  ["}", "vi_vis_paragraph"],
+  ["{", "vi_vis_paragraph_back"],
+  [")", "vi_vis_sentence"],
+  ["(", "vi_vis_sentence_back"],
 
   // Switch to line mode
   ["V", "vi_visual_toggle_line"],
@@ -1856,6 +1958,10 @@ const navCommands = [
   ["vi_line_end", "move_line_end"],
   ["vi_doc_start", "move_doc_start"],
   ["vi_doc_end", "move_doc_end"],
+  ["vi_paragraph", "move_paragraph"],
+  ["vi_paragraph_back", "move_paragraph_back"],
+  ["vi_sentence", "move_sentence"],
+  ["vi_sentence_back", "move_sentence_back"],
   ["vi_page_down", "page_down"],
   ["vi_page_up", "page_up"],
   ["vi_half_page_down", "half_page_down"],
@@ -0,0 +1,511 @@
+//! Popups anchored to text, for plugin hover previews
+//!
+//! Plugins build tooltip-style UIs (a swatch for the color under the mouse,
+//! a preview of a link) from two pieces:
+//!
+//! - the `mouse_moved_in_buffer` hook, which reports the buffer position
+//!   under the mouse. It fires only over characters of the buffer, and at
+//!   most every [`MOUSE_MOVED_IN_BUFFER_INTERVAL`]: moves in between are
+//!   dropped except the last, which is sent once the interval has passed.
+//! - `showAnchoredPopup`, which shows a small borderless popup below the
+//!   line of a byte position, or above it when there is no room below.
+//!
+//! There is one anchored popup at a time; showing another replaces it. It
+//! closes when the mouse or the cursor moves off the word it points at
+//! (the run of non-whitespace around the anchor), when its timeout passes,
+//! or when the plugin closes it. The LSP hover popup takes precedence: it
+//! closes the anchored popup, and an anchored popup isn't shown while a
+//! built-in popup is open.
+
+use std::ops::Range;
+use std::time::{Duration, Instant};
+
+use ratatui::layout::Rect;
+use ratatui::Frame;
+
+use crate::model::event::{BufferId, SplitId};
+use crate::primitives::display_width::str_width;
+use crate::services::plugins::hooks::HookArgs;
+use crate::view::popup::Popup;
+
+use super::Editor;
+
+/// Shortest time between two `mouse_moved_in_buffer` hooks
+pub const MOUSE_MOVED_IN_BUFFER_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Tallest an anchored popup gets; longer content scrolls
+const MAX_HEIGHT: u16 = 12;
+
+/// How far either side of the anchor to look for the end of its word
+const WORD_SEARCH_BYTES: usize = 256;
+
+/// Buffer text under the mouse, as sent to `mouse_moved_in_buffer`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct TextUnderMouse {
+    pub buffer_id: BufferId,
+    pub split_id: SplitId,
+    pub byte_offset: usize,
+    pub screen_row: u16,
+    pub screen_col: u16,
+}
+
+/// Lets through at most one value per interval
+///
+/// A value offered too soon after the last one is held, replacing any held
+/// before it, until [`Throttle::poll`] finds the interval has passed.
+#[derive(Debug)]
+pub(crate) struct Throttle<T> {
+    interval: Duration,
+    last_sent: Option<Instant>,
+    pending: Option<T>,
+}
+
+impl<T> Throttle<T> {
+    pub fn new(interval: Duration) -> Self {
+        Self {
+            interval,
+            last_sent: None,
+            pending: None,
+        }
+    }
+
+    /// Offer a value; returns it if it can be sent now
+    pub fn offer(&mut self, now: Instant, value: T) -> Option<T> {
+        if self
+            .last_sent
+            .is_some_and(|last| now.saturating_duration_since(last) < self.interval)
+        {
+            self.pending = Some(value);
+            return None;
+        }
+        self.pending = None;
+        self.last_sent = Some(now);
+        Some(value)
+    }
+
+    /// The held value, once its interval has passed
+    pub fn poll(&mut self, now: Instant) -> Option<T> {
+        self.pending.as_ref()?;
+        if self
+            .last_sent
+            .is_some_and(|last| now.saturating_duration_since(last) < self.interval)
+        {
+            return None;
+        }
+        self.last_sent = Some(now);
+        self.pending.take()
+    }
+
+    /// Drop the held value
+    pub fn cancel(&mut self) {
+        self.pending = None;
+    }
+}
+
+/// Area of a `width` x `height` popup for an anchor at screen cell `anchor`
+///
+/// The popup goes below the anchor's row, or above it when it doesn't fit
+/// below. When it fits on neither side it takes the taller one and is cut to
+/// fit. It starts at the anchor's column, moved left as far as needed to stay
+/// within `bounds`.
+pub(crate) fn anchored_area(anchor: (u16, u16), width: u16, height: u16, bounds: Rect) -> Rect {
+    let (anchor_x, anchor_y) = anchor;
+    let width = width.min(bounds.width);
+    let below = bounds.bottom().saturating_sub(anchor_y.saturating_add(1));
+    let above = anchor_y.saturating_sub(bounds.y);
+    let (y, height) = if height <= below || below >= above {
+        (anchor_y + 1, height.min(below))
+    } else {
+        let height = height.min(above);
+        (anchor_y - height, height)
+    };
+    let x = anchor_x
+        .min(bounds.right().saturating_sub(width))
+        .max(bounds.x);
+    Rect::new(x, y, width, height)
+}
+
+/// A popup shown by a plugin with showAnchoredPopup
+#[derive(Debug)]
+pub(crate) struct AnchoredPopup {
+    buffer_id: BufferId,
+    position: usize,
+    popup: Popup,
+    /// Byte range of the word at the anchor; the popup stays open while the
+    /// mouse and cursor don't leave it
+    word: Range<usize>,
+    /// Primary cursor position when the popup was shown
+    cursor: usize,
+    expires_at: Option<Instant>,
+    /// Where the popup was last drawn, for keeping it open while the mouse
+    /// is over it
+    area: Option<Rect>,
+}
+
+/// Hook throttling and the popup shown, see [`Editor::show_anchored_popup`]
+#[derive(Debug)]
+pub(crate) struct AnchoredPopupState {
+    mouse_moves: Throttle<TextUnderMouse>,
+    /// Last position offered to the hook, so moves within one character
+    /// don't fire it again
+    last_text: Option<TextUnderMouse>,
+    popup: Option<AnchoredPopup>,
+}
+
+impl Default for AnchoredPopupState {
+    fn default() -> Self {
+        Self {
+            mouse_moves: Throttle::new(MOUSE_MOVED_IN_BUFFER_INTERVAL),
+            last_text: None,
+            popup: None,
+        }
+    }
+}
+
+impl Editor {
+    /// Show a plugin's anchored popup, replacing the previous one
+    ///
+    /// Ignored while the LSP hover popup (or any transient popup) is open.
+    pub(super) fn show_anchored_popup(
+        &mut self,
+        buffer_id: BufferId,
+        position: usize,
+        lines: Vec<String>,
+        max_width: u16,
+        timeout_ms: Option<u64>,
+    ) {
+        self.anchored_popup.popup = None;
+        if self.has_transient_popup() {
+            return;
+        }
+        let Some(state) = self.buffers.get_mut(&buffer_id) else {
+            return;
+        };
+        let position = state
+            .buffer
+            .snap_to_char_boundary(position.min(state.buffer.len()));
+        let cursor = state.cursors.primary().position;
+        let word = word_around(state, position);
+
+        let longest = lines.iter().map(|line| str_width(line)).max().unwrap_or(0);
+        let mut popup = Popup::text(lines, &self.theme);
+        popup.transient = true;
+        popup.bordered = false;
+        // The text renderer keeps two columns for a scrollbar
+        popup.width = (longest.min(u16::MAX as usize) as u16)
+            .saturating_add(2)
+            .min(max_width.max(3));
+        popup.max_height = MAX_HEIGHT;
+
+        let expires_at = timeout_ms
+            .filter(|ms| *ms > 0)
+            .map(|ms| self.time_source.now() + Duration::from_millis(ms));
+        self.anchored_popup.popup = Some(AnchoredPopup {
+            buffer_id,
+            position,
+            popup,
+            word,
+            cursor,
+            expires_at,
+            area: None,
+        });
+    }
+
+    /// Close the anchored popup, if one is open
+    ///
+    /// Returns true if one was open.
+    pub(super) fn close_anchored_popup(&mut self) -> bool {
+        self.anchored_popup.popup.take().is_some()
+    }
+
+    /// Whether a plugin's anchored popup is open
+    pub fn has_anchored_popup(&self) -> bool {
+        self.anchored_popup.popup.is_some()
+    }
+
+    /// Screen area the anchored popup was last drawn in
+    pub fn anchored_popup_area(&self) -> Option<Rect> {
+        self.anchored_popup.popup.as_ref().and_then(|p| p.area)
+    }
+
+    /// Buffer character under the mouse, if the mouse is over one
+    ///
+    /// The gutter, virtual text and the space past the end of a line don't
+    /// count.
+    pub(super) fn text_under_mouse(&mut self, col: u16, row: u16) -> Option<TextUnderMouse> {
+        let (split_id, buffer_id, content_rect) = self
+            .cached_layout
+            .split_areas
+            .iter()
+            .find(|(_, _, rect, _, _, _)| {
+                col >= rect.x && col < rect.right() && row >= rect.y && row < rect.bottom()
+            })
+            .map(|(split_id, buffer_id, rect, _, _, _)| (*split_id, *buffer_id, *rect))?;
+        let gutter_width = self.buffers.get(&buffer_id)?.margins.left_total_width() as u16;
+        let text_col = col.checked_sub(content_rect.x + gutter_width)? as usize;
+        let mapping = self
+            .cached_layout
+            .view_line_mappings
+            .get(&split_id)?
+            .get((row - content_rect.y) as usize)?;
+        let byte_offset = mapping.source_byte_at_visual_col(text_col)?;
+
+        let state = self.buffers.get_mut(&buffer_id)?;
+        let end = state.buffer.next_char_boundary(byte_offset);
+        let character = state.get_text_range(byte_offset, end);
+        if character.is_empty() || character == "\n" || character == "\r\n" || character == "\r" {
+            return None;
+        }
+        Some(TextUnderMouse {
+            buffer_id,
+            split_id,
+            byte_offset,
+            screen_row: row,
+            screen_col: col,
+        })
+    }
+
+    /// Fire `mouse_moved_in_buffer` for a mouse move, throttled, and close the
+    /// anchored popup when the mouse leaves its word
+    ///
+    /// Returns true if the popup closed and the screen needs a redraw.
+    pub(super) fn handle_mouse_moved_in_buffer(&mut self, col: u16, row: u16) -> bool {
+        let wants_hook = self
+            .plugin_manager
+            .has_hook_handlers("mouse_moved_in_buffer");
+        if !wants_hook && self.anchored_popup.popup.is_none() {
+            return false;
+        }
+        let text = self.text_under_mouse(col, row);
+
+        let mut closed = false;
+        if let Some(anchored) = &self.anchored_popup.popup {
+            let over_popup = anchored.area.is_some_and(|area| {
+                col >= area.x && col < area.right() && row >= area.y && row < area.bottom()
+            });
+            let on_word = text.is_some_and(|text| {
+                text.buffer_id == anchored.buffer_id && anchored.word.contains(&text.byte_offset)
+            });
+            if !over_popup && !on_word {
+                self.anchored_popup.popup = None;
+                closed = true;
+            }
+        }
+
+        if !wants_hook {
+            return closed;
+        }
+        let same_character = |a: &TextUnderMouse, b: &TextUnderMouse| {
+            a.buffer_id == b.buffer_id && a.split_id == b.split_id && a.byte_offset == b.byte_offset
+        };
+        match text {
+            Some(text)
+                if self
+                    .anchored_popup
+                    .last_text
+                    .is_some_and(|last| same_character(&last, &text)) => {}
+            Some(text) => {
+                self.anchored_popup.last_text = Some(text);
+                let now = self.time_source.now();
+                if let Some(text) = self.anchored_popup.mouse_moves.offer(now, text) {
+                    self.fire_mouse_moved_in_buffer(text);
+                }
+            }
+            None => {
+                // Off the text: a held move no longer describes the mouse
+                self.anchored_popup.last_text = None;
+                self.anchored_popup.mouse_moves.cancel();
+            }
+        }
+        closed
+    }
+
+    fn fire_mouse_moved_in_buffer(&self, text: TextUnderMouse) {
+        self.plugin_manager.run_hook(
+            "mouse_moved_in_buffer",
+            HookArgs::MouseMovedInBuffer {
+                buffer_id: text.buffer_id,
+                split_id: text.split_id,
+                byte_offset: text.byte_offset,
+                screen_row: text.screen_row,
+                screen_col: text.screen_col,
+            },
+        );
+    }
+
+    /// Send a held mouse move once its interval passed, and close the
+    /// anchored popup when it timed out, its buffer went away or the cursor
+    /// left its word
+    ///
+    /// Returns true if the popup closed and the screen needs a redraw.
+    pub(super) fn check_anchored_popup(&mut self) -> bool {
+        let now = self.time_source.now();
+        if let Some(text) = self.anchored_popup.mouse_moves.poll(now) {
+            self.fire_mouse_moved_in_buffer(text);
+        }
+
+        let Some(anchored) = &self.anchored_popup.popup else {
+            return false;
+        };
+        let expired = anchored.expires_at.is_some_and(|at| now >= at);
+        let cursor_left = match self.buffers.get(&anchored.buffer_id) {
+            Some(state) => {
+                let cursor = state.cursors.primary().position;
+                cursor != anchored.cursor && !anchored.word.contains(&cursor)
+            }
+            None => true,
+        };
+        if expired || cursor_left || self.active_buffer() != anchored.buffer_id {
+            self.anchored_popup.popup = None;
+            return true;
+        }
+        false
+    }
+
+    /// Draw the anchored popup below (or above) its anchor
+    ///
+    /// Drawn before the built-in popups, and not at all while one is open.
+    pub(super) fn render_anchored_popup(&mut self, frame: &mut Frame, bounds: Rect) {
+        let Some(buffer_id) = self.anchored_popup.popup.as_ref().map(|p| p.buffer_id) else {
+            return;
+        };
+        let popup_open = self
+            .buffers
+            .get(&buffer_id)
+            .is_some_and(|state| state.popups.is_visible());
+        let anchor = self.anchor_screen_position(buffer_id);
+        let Some(anchored) = self.anchored_popup.popup.as_mut() else {
+            return;
+        };
+        anchored.area = None;
+        let Some(anchor) = anchor.filter(|_| !popup_open) else {
+            return;
+        };
+        let height = anchored
+            .popup
+            .content_height()
+            .min(anchored.popup.max_height);
+        let area = anchored_area(anchor, anchored.popup.width, height, bounds);
+        if area.width == 0 || area.height == 0 {
+            return;
+        }
+        anchored.popup.render(frame, area, &self.theme);
+        anchored.area = Some(area);
+    }
+
+    /// Screen cell of the anchored popup's position in the split showing
+    /// `buffer_id`, preferring the active split
+    fn anchor_screen_position(&self, buffer_id: BufferId) -> Option<(u16, u16)> {
+        let position = self.anchored_popup.popup.as_ref()?.position;
+        let active_split = self.split_manager.active_split();
+        let mut splits: Vec<_> = self
+            .cached_layout
+            .split_areas
+            .iter()
+            .filter(|(_, id, _, _, _, _)| *id == buffer_id)
+            .map(|(split_id, _, rect, _, _, _)| (*split_id, *rect))
+            .collect();
+        splits.sort_by_key(|(split_id, _)| *split_id != active_split);
+        let gutter_width = self.buffers.get(&buffer_id)?.margins.left_total_width() as u16;
+
+        splits.into_iter().find_map(|(split_id, rect)| {
+            let mappings = self.cached_layout.view_line_mappings.get(&split_id)?;
+            mappings.iter().enumerate().find_map(|(row, mapping)| {
+                let char_index = mapping
+                    .char_source_bytes
+                    .iter()
+                    .position(|byte| *byte == Some(position))?;
+                let col = mapping
+                    .visual_to_char
+                    .iter()
+                    .position(|index| *index == char_index)?;
+                let x = rect.x + gutter_width + col as u16;
+                (x < rect.right()).then_some((x, rect.y + row as u16))
+            })
+        })
+    }
+}
+
+/// Byte range of the non-whitespace run around `position`
+fn word_around(state: &mut crate::state::EditorState, position: usize) -> Range<usize> {
+    let len = state.buffer.len();
+    let start = state
+        .buffer
+        .snap_to_char_boundary(position.saturating_sub(WORD_SEARCH_BYTES));
+    let end = state
+        .buffer
+        .snap_to_char_boundary((position + WORD_SEARCH_BYTES).min(len));
+    let text = state.get_text_range(start, end);
+    let offset = position - start;
+    if !text.is_char_boundary(offset) {
+        return position..position + 1;
+    }
+    let word_start = text[..offset]
+        .rfind(char::is_whitespace)
+        .map_or(0, |index| {
+            index + text[index..].chars().next().map_or(1, char::len_utf8)
+        });
+    let word_end = text[offset..]
+        .find(char::is_whitespace)
+        .map_or(text.len(), |index| offset + index);
+    // An anchor on whitespace still keeps its own character
+    (start + word_start)..(start + word_end.max(offset + 1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_throttle_sends_the_latest_held_value_after_the_interval() {
+        let start = Instant::now();
+        let ms = |n| start + Duration::from_millis(n);
+        let mut throttle = Throttle::new(Duration::from_millis(50));
+
+        assert_eq!(throttle.offer(ms(0), 1), Some(1));
+        assert_eq!(throttle.offer(ms(10), 2), None);
+        assert_eq!(throttle.offer(ms(20), 3), None);
+        assert_eq!(throttle.poll(ms(40)), None);
+        assert_eq!(throttle.poll(ms(50)), Some(3));
+        assert_eq!(throttle.poll(ms(200)), None);
+
+        // The held value was sent at 50, so the interval restarts there
+        assert_eq!(throttle.offer(ms(60), 4), None);
+        throttle.cancel();
+        assert_eq!(throttle.poll(ms(500)), None);
+        assert_eq!(throttle.offer(ms(500), 5), Some(5));
+    }
+
+    #[test]
+    fn test_anchored_area_goes_below_and_flips_above_near_the_bottom() {
+        let bounds = Rect::new(0, 1, 80, 20);
+
+        // Room below: the row after the anchor
+        assert_eq!(
+            anchored_area((10, 5), 20, 3, bounds),
+            Rect::new(10, 6, 20, 3)
+        );
+
+        // Near the bottom edge: ending on the row before the anchor
+        assert_eq!(
+            anchored_area((10, 19), 20, 3, bounds),
+            Rect::new(10, 16, 20, 3)
+        );
+
+        // Near the right edge: moved left to stay on screen
+        assert_eq!(
+            anchored_area((75, 5), 20, 3, bounds),
+            Rect::new(60, 6, 20, 3)
+        );
+
+        // Fits on neither side: the taller side, cut to fit
+        assert_eq!(
+            anchored_area((0, 15), 10, 30, bounds),
+            Rect::new(0, 1, 10, 14)
+        );
+        assert_eq!(
+            anchored_area((0, 3), 10, 30, bounds),
+            Rect::new(0, 4, 10, 17)
+        );
+    }
+}
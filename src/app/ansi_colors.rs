@@ -0,0 +1,319 @@
+//! ANSI colors shown as overlays
+//!
+//! Virtual buffers created with `parse_ansi` and file buffers with
+//! "Interpret ANSI Colors" turned on hold their text with the escape
+//! sequences stripped, so cursor positions, search and text properties all
+//! refer to what is on screen. The colors become overlays in the "ansi"
+//! namespace.
+
+use rust_i18n::t;
+
+use super::Editor;
+use crate::model::event::{BufferId, EventLog};
+use crate::primitives::ansi::{AnsiSpan, AnsiStripper};
+use crate::primitives::text_property::TextPropertyEntry;
+use crate::view::overlay::{Overlay, OverlayFace, OverlayNamespace};
+
+/// Namespace of the color overlays
+const ANSI_NAMESPACE: &str = "ansi";
+
+/// One entry of a virtual buffer's content, as parsed
+#[derive(Debug)]
+struct ParsedEntry {
+    /// The entry's text as the plugin sent it
+    raw: String,
+    /// The same text without escape sequences
+    text: String,
+    spans: Vec<AnsiSpan>,
+    /// Parser state after this entry, to continue from
+    stripper: AnsiStripper,
+}
+
+/// Parsed content of a virtual buffer created with `parse_ansi`
+///
+/// Streaming panels resend their content with new entries appended; the
+/// entries unchanged since the last update are not parsed again.
+#[derive(Debug, Default)]
+pub(super) struct AnsiEntryCache {
+    entries: Vec<ParsedEntry>,
+}
+
+impl AnsiEntryCache {
+    /// Strip the escape sequences from `entries`, returning the entries to
+    /// insert and the colored spans of the resulting text
+    fn strip(
+        &mut self,
+        entries: Vec<TextPropertyEntry>,
+    ) -> (Vec<TextPropertyEntry>, Vec<AnsiSpan>) {
+        let unchanged = self
+            .entries
+            .iter()
+            .zip(&entries)
+            .take_while(|(parsed, entry)| parsed.raw == entry.text)
+            .count();
+        self.entries.truncate(unchanged);
+        let mut stripper = self
+            .entries
+            .last()
+            .map(|parsed| parsed.stripper.clone())
+            .unwrap_or_default();
+
+        let mut stripped = Vec::with_capacity(entries.len());
+        for (i, entry) in entries.into_iter().enumerate() {
+            if i >= unchanged {
+                let (text, spans) = stripper.push(&entry.text);
+                self.entries.push(ParsedEntry {
+                    raw: entry.text,
+                    text,
+                    spans,
+                    stripper: stripper.clone(),
+                });
+            }
+            stripped.push(TextPropertyEntry {
+                text: self.entries[i].text.clone(),
+                properties: entry.properties,
+            });
+        }
+
+        let spans = self
+            .entries
+            .iter()
+            .flat_map(|parsed| parsed.spans.iter().cloned())
+            .collect();
+        (stripped, spans)
+    }
+
+    /// Strip the escape sequences from `entries` coming after the parsed
+    /// ones, returning the entries to insert and the colored spans of their
+    /// text
+    fn append(
+        &mut self,
+        entries: Vec<TextPropertyEntry>,
+    ) -> (Vec<TextPropertyEntry>, Vec<AnsiSpan>) {
+        let mut stripper = self
+            .entries
+            .last()
+            .map(|parsed| parsed.stripper.clone())
+            .unwrap_or_default();
+        let mut spans = Vec::new();
+        let mut stripped = Vec::with_capacity(entries.len());
+        for entry in entries {
+            let (text, entry_spans) = stripper.push(&entry.text);
+            spans.extend(entry_spans.iter().cloned());
+            stripped.push(TextPropertyEntry {
+                text: text.clone(),
+                properties: entry.properties,
+            });
+            self.entries.push(ParsedEntry {
+                raw: entry.text,
+                text,
+                spans: entry_spans,
+                stripper: stripper.clone(),
+            });
+        }
+        (stripped, spans)
+    }
+}
+
+/// A file buffer showing its ANSI colors
+#[derive(Debug)]
+pub(super) struct AnsiFileView {
+    /// The file's text with its escape sequences, put back when turned off
+    raw: String,
+    /// Whether editing was disabled before the colors were shown
+    editing_disabled: bool,
+}
+
+impl Editor {
+    /// Have a virtual buffer's content parsed for ANSI colors from now on
+    pub fn set_virtual_buffer_parse_ansi(&mut self, buffer_id: BufferId, parse_ansi: bool) {
+        if parse_ansi {
+            self.ansi_entry_caches.entry(buffer_id).or_default();
+        } else {
+            self.ansi_entry_caches.remove(&buffer_id);
+        }
+    }
+
+    /// Strip virtual buffer entries if the buffer parses ANSI colors
+    ///
+    /// Returns the entries to insert and, for such buffers, the colors to
+    /// apply once they are in.
+    pub(super) fn strip_ansi_entries(
+        &mut self,
+        buffer_id: BufferId,
+        entries: Vec<TextPropertyEntry>,
+    ) -> (Vec<TextPropertyEntry>, Option<Vec<AnsiSpan>>) {
+        match self.ansi_entry_caches.get_mut(&buffer_id) {
+            Some(cache) => {
+                let (entries, spans) = cache.strip(entries);
+                (entries, Some(spans))
+            }
+            None => (entries, None),
+        }
+    }
+
+    /// Strip entries appended to a virtual buffer if the buffer parses ANSI
+    /// colors, like [`Self::strip_ansi_entries`]; the colors are those of the
+    /// appended text only
+    pub(super) fn strip_appended_ansi_entries(
+        &mut self,
+        buffer_id: BufferId,
+        entries: Vec<TextPropertyEntry>,
+    ) -> (Vec<TextPropertyEntry>, Option<Vec<AnsiSpan>>) {
+        match self.ansi_entry_caches.get_mut(&buffer_id) {
+            Some(cache) => {
+                let (entries, spans) = cache.append(entries);
+                (entries, Some(spans))
+            }
+            None => (entries, None),
+        }
+    }
+
+    /// Replace a buffer's color overlays
+    pub(super) fn apply_ansi_spans(&mut self, buffer_id: BufferId, spans: &[AnsiSpan]) {
+        let Some(state) = self.buffers.get_mut(&buffer_id) else {
+            return;
+        };
+        let namespace = OverlayNamespace::from_string(ANSI_NAMESPACE.to_string());
+        state
+            .overlays
+            .clear_namespace(&namespace, &mut state.marker_list);
+        self.add_ansi_spans(buffer_id, spans);
+    }
+
+    /// Add color overlays to a buffer, keeping the ones it has
+    pub(super) fn add_ansi_spans(&mut self, buffer_id: BufferId, spans: &[AnsiSpan]) {
+        let Some(state) = self.buffers.get_mut(&buffer_id) else {
+            return;
+        };
+        let namespace = OverlayNamespace::from_string(ANSI_NAMESPACE.to_string());
+        for span in spans {
+            let overlay = Overlay::with_namespace(
+                &mut state.marker_list,
+                span.range.clone(),
+                OverlayFace::Style { style: span.style },
+                namespace.clone(),
+            );
+            state.overlays.add(overlay, &state.marker_list);
+        }
+    }
+
+    /// Toggle showing the ANSI colors of the active file buffer
+    pub fn toggle_ansi_colors(&mut self) {
+        let buffer_id = self.active_buffer();
+        if self.ansi_file_views.contains_key(&buffer_id) {
+            self.hide_ansi_colors(buffer_id);
+            self.set_status_message(t!("ansi.hidden").to_string());
+            return;
+        }
+        match self.show_ansi_colors(buffer_id) {
+            Ok(count) => self.set_status_message(t!("ansi.shown", count = count).to_string()),
+            Err(message) => self.set_status_message(message),
+        }
+    }
+
+    /// Whether a file buffer is showing its ANSI colors
+    pub fn is_showing_ansi_colors(&self, buffer_id: BufferId) -> bool {
+        self.ansi_file_views.contains_key(&buffer_id)
+    }
+
+    /// Strip a file buffer's escape sequences and show them as colors
+    ///
+    /// Returns the number of colored spans, or why the buffer can't show
+    /// its colors.
+    fn show_ansi_colors(&mut self, buffer_id: BufferId) -> Result<usize, String> {
+        let is_file = self
+            .buffer_metadata
+            .get(&buffer_id)
+            .is_some_and(|m| !m.is_virtual() && m.file_path().is_some());
+        let Some(state) = self.buffers.get(&buffer_id).filter(|_| is_file) else {
+            return Err(t!("ansi.unavailable").to_string());
+        };
+        if state.buffer.is_modified() {
+            return Err(t!("ansi.save_first").to_string());
+        }
+        // Binary files and large files that aren't fully loaded stay as they are
+        let raw = match state.buffer.to_string() {
+            Some(raw) if !state.buffer.is_binary() => raw,
+            _ => return Err(t!("ansi.unavailable").to_string()),
+        };
+
+        let (text, spans) = AnsiStripper::new().push(&raw);
+        let editing_disabled = state.editing_disabled;
+        self.replace_buffer_text(buffer_id, &text);
+        if let Some(state) = self.buffers.get_mut(&buffer_id) {
+            state.editing_disabled = true;
+        }
+        self.apply_ansi_spans(buffer_id, &spans);
+        self.ansi_file_views.insert(
+            buffer_id,
+            AnsiFileView {
+                raw,
+                editing_disabled,
+            },
+        );
+        Ok(spans.len())
+    }
+
+    /// Put back the escape sequences of a file buffer showing its colors
+    fn hide_ansi_colors(&mut self, buffer_id: BufferId) {
+        let Some(view) = self.ansi_file_views.remove(&buffer_id) else {
+            return;
+        };
+        self.apply_ansi_spans(buffer_id, &[]);
+        self.replace_buffer_text(buffer_id, &view.raw);
+        if let Some(state) = self.buffers.get_mut(&buffer_id) {
+            state.editing_disabled = view.editing_disabled;
+        }
+    }
+
+    /// Show the colors again after a file buffer showing them was reloaded
+    /// from disk
+    pub(super) fn refresh_ansi_colors(&mut self, buffer_id: BufferId) {
+        let Some(view) = self.ansi_file_views.remove(&buffer_id) else {
+            return;
+        };
+        if let Some(state) = self.buffers.get_mut(&buffer_id) {
+            state.editing_disabled = view.editing_disabled;
+        }
+        if let Err(message) = self.show_ansi_colors(buffer_id) {
+            self.set_status_message(message);
+        }
+    }
+
+    /// Forget the ANSI state of a closed buffer
+    pub(super) fn forget_ansi_colors(&mut self, buffer_id: BufferId) {
+        self.ansi_entry_caches.remove(&buffer_id);
+        self.ansi_file_views.remove(&buffer_id);
+    }
+
+    /// Swap a file buffer's text without recording an edit
+    ///
+    /// Undo history refers to the old text, so it is dropped.
+    fn replace_buffer_text(&mut self, buffer_id: BufferId, text: &str) {
+        let Some(state) = self.buffers.get_mut(&buffer_id) else {
+            return;
+        };
+        let len = state.buffer.len();
+        if len > 0 {
+            state.buffer.delete_bytes(0, len);
+        }
+        state.buffer.insert(0, text);
+        state.buffer.clear_modified();
+
+        let new_len = state.buffer.len();
+        let mut cursors = state.cursors.clone();
+        cursors.map(|cursor| {
+            cursor.position = state
+                .buffer
+                .snap_to_char_boundary(cursor.position.min(new_len));
+            cursor.clear_selection();
+        });
+        state.cursors = cursors;
+
+        if let Some(event_log) = self.event_logs.get_mut(&buffer_id) {
+            *event_log = EventLog::new();
+        }
+        self.seen_byte_ranges.remove(&buffer_id);
+    }
+}
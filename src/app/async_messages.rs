@@ -9,12 +9,15 @@
 
 use crate::model::event::BufferId;
 use crate::services::async_bridge::{LspMessageType, LspProgressValue, LspServerStatus};
+use crate::services::fs::FsMetadata;
+use crate::services::lsp::manager::{label_language, label_server};
+use crate::services::lsp::uri::normalize_uri;
 use crate::view::file_tree::{FileTreeView, NodeId};
 use lsp_types::{Diagnostic, InlayHint};
 use rust_i18n::t;
 use serde_json::Value;
 use std::path::PathBuf;
-use std::time::{Duration, Instant};
+use std::time::{Duration, SystemTime};
 
 use super::types::{LspMessageEntry, LspProgressInfo};
 use super::Editor;
@@ -26,12 +29,14 @@ use super::Editor;
 impl Editor {
     /// Find a buffer by its LSP URI
     ///
-    /// This is a common pattern used by diagnostics, inlay hints, and other LSP handlers
+    /// This is a common pattern used by diagnostics, inlay hints, and other LSP handlers.
+    /// The URI is compared in its normalized form, so differently escaped
+    /// spellings of the same file match.
     pub(super) fn find_buffer_by_uri(&self, uri: &str) -> Option<BufferId> {
-        let parsed_uri = uri.parse::<lsp_types::Uri>().ok()?;
+        let uri = normalize_uri(uri);
         self.buffer_metadata
             .iter()
-            .find(|(_, m)| m.file_uri() == Some(&parsed_uri))
+            .find(|(_, m)| m.file_uri().map(|u| u.as_str()) == Some(uri.as_str()))
             .map(|(buffer_id, _)| *buffer_id)
     }
 
@@ -43,10 +48,12 @@ impl Editor {
         diagnostics: &[Diagnostic],
     ) -> Option<BufferId> {
         let buffer_id = self.find_buffer_by_uri(uri)?;
+        let encoding = self.lsp_position_encoding(buffer_id);
         let state = self.buffers.get_mut(&buffer_id)?;
         crate::services::lsp::diagnostics::apply_diagnostics_to_state_cached(
             state,
             diagnostics,
+            encoding,
             &self.theme,
         );
         Some(buffer_id)
@@ -59,14 +66,33 @@ impl Editor {
 
 impl Editor {
     /// Store and apply diagnostics, emit hook for plugins
-    fn store_and_apply_diagnostics(&mut self, uri: String, diagnostics: Vec<Diagnostic>) {
-        // Store diagnostics for later retrieval by plugins
-        if diagnostics.is_empty() {
-            self.stored_diagnostics.remove(&uri);
-        } else {
-            self.stored_diagnostics
-                .insert(uri.clone(), diagnostics.clone());
+    ///
+    /// Diagnostics are stored by the file the URI names, so a server spelling
+    /// one file two ways doesn't split its diagnostics across two entries.
+    /// Those of one of a language's several servers are kept beside the
+    /// others', and name the server as their source if they don't say.
+    fn store_and_apply_diagnostics(
+        &mut self,
+        server: &str,
+        uri: String,
+        mut diagnostics: Vec<Diagnostic>,
+    ) {
+        let uri = normalize_uri(&uri);
+        if let Some(name) = label_server(server) {
+            for diagnostic in &mut diagnostics {
+                diagnostic.source.get_or_insert_with(|| name.to_string());
+            }
         }
+        // Store diagnostics for later retrieval by plugins and the panel
+        let file = self
+            .stored_diagnostics
+            .publish_from(server, &uri, diagnostics);
+        self.refresh_workspace_diagnostics_file(&file);
+        let diagnostics = self
+            .stored_diagnostics
+            .get_file(&file)
+            .map(|entry| entry.diagnostics.clone())
+            .unwrap_or_default();
 
         if let Some(buffer_id) = self.apply_diagnostics_to_buffer(&uri, &diagnostics) {
             tracing::info!(
@@ -89,18 +115,25 @@ impl Editor {
     }
 
     /// Handle LSP diagnostics (push model)
-    pub(super) fn handle_lsp_diagnostics(&mut self, uri: String, diagnostics: Vec<Diagnostic>) {
+    pub(super) fn handle_lsp_diagnostics(
+        &mut self,
+        language: String,
+        uri: String,
+        diagnostics: Vec<Diagnostic>,
+    ) {
         tracing::debug!(
-            "Processing {} LSP diagnostics for {}",
+            "Processing {} LSP ({}) diagnostics for {}",
             diagnostics.len(),
+            language,
             uri
         );
-        self.store_and_apply_diagnostics(uri, diagnostics);
+        self.store_and_apply_diagnostics(&language, uri, diagnostics);
     }
 
     /// Handle LSP pulled diagnostics (pull model - LSP 3.17+)
     pub(super) fn handle_lsp_pulled_diagnostics(
         &mut self,
+        language: String,
         uri: String,
         result_id: Option<String>,
         diagnostics: Vec<Diagnostic>,
@@ -124,10 +157,11 @@ impl Editor {
 
         // Store result_id for incremental updates
         if let Some(result_id) = result_id {
-            self.diagnostic_result_ids.insert(uri.clone(), result_id);
+            self.diagnostic_result_ids
+                .insert((language.clone(), normalize_uri(&uri)), result_id);
         }
 
-        self.store_and_apply_diagnostics(uri, diagnostics);
+        self.store_and_apply_diagnostics(&language, uri, diagnostics);
     }
 }
 
@@ -161,8 +195,9 @@ impl Editor {
         );
 
         if let Some(buffer_id) = self.find_buffer_by_uri(&uri) {
+            let encoding = self.lsp_position_encoding(buffer_id);
             if let Some(state) = self.buffers.get_mut(&buffer_id) {
-                Self::apply_inlay_hints_to_state(state, &hints);
+                Self::apply_inlay_hints_to_state(state, &hints, encoding);
                 tracing::info!(
                     "Applied {} inlay hints as virtual text to buffer {:?}",
                     hints.len(),
@@ -190,7 +225,11 @@ impl Editor {
             return;
         };
 
-        let Some(client) = lsp.get_or_spawn(&language) else {
+        // Inlay hints come from the primary server only
+        if lsp.server_labels(label_language(&language)).first() != Some(&language) {
+            return;
+        }
+        let Some(client) = lsp.get_or_spawn_server(&language) else {
             return;
         };
 
@@ -286,7 +325,7 @@ impl Editor {
             language: language.clone(),
             message_type,
             message: message.clone(),
-            timestamp: Instant::now(),
+            timestamp: self.time_source.now(),
         });
 
         // Keep only last 100 messages
@@ -316,7 +355,7 @@ impl Editor {
             language,
             message_type,
             message,
-            timestamp: Instant::now(),
+            timestamp: self.time_source.now(),
         });
 
         // Keep only last 500 log messages
@@ -340,11 +379,26 @@ impl Editor {
         // Update warning domain for LSP status indicator
         self.update_lsp_warning_domain();
 
+        // A server that stops or starts over agrees to its encoding and sync anew
+        if !matches!(
+            status,
+            LspServerStatus::Running | LspServerStatus::Unresponsive
+        ) {
+            self.forget_lsp_server_sync(&language);
+        }
+
         // Handle server crash - trigger auto-restart
         if status == LspServerStatus::Error {
             let was_running = old_status
                 .as_ref()
-                .map(|s| matches!(s, LspServerStatus::Running | LspServerStatus::Initializing))
+                .map(|s| {
+                    matches!(
+                        s,
+                        LspServerStatus::Running
+                            | LspServerStatus::Initializing
+                            | LspServerStatus::Unresponsive
+                    )
+                })
                 .unwrap_or(false);
 
             if was_running {
@@ -360,6 +414,7 @@ impl Editor {
             LspServerStatus::Starting => "starting",
             LspServerStatus::Initializing => "initializing",
             LspServerStatus::Running => "running",
+            LspServerStatus::Unresponsive => "unresponsive",
             LspServerStatus::Error => "error",
             LspServerStatus::Shutdown => "shutdown",
         };
@@ -368,6 +423,7 @@ impl Editor {
                 LspServerStatus::Starting => "starting",
                 LspServerStatus::Initializing => "initializing",
                 LspServerStatus::Running => "running",
+                LspServerStatus::Unresponsive => "unresponsive",
                 LspServerStatus::Error => "error",
                 LspServerStatus::Shutdown => "shutdown",
             })
@@ -467,21 +523,16 @@ impl Editor {
         const DEBOUNCE_WINDOW: Duration = Duration::from_secs(10);
         const RAPID_REVERT_THRESHOLD: u32 = 10; // Require 10 reverts in 10 seconds to disable
 
-        // Skip if auto-revert is disabled
-        if !self.auto_revert_enabled {
-            return false;
-        }
-
         let path_buf = PathBuf::from(&path);
 
-        // Only track events for files that are actually open in the editor
-        let is_file_open = self
-            .buffers
-            .iter()
-            .any(|(_, state)| state.buffer.file_path() == Some(&path_buf));
+        // Only track events for files that are open in the editor with
+        // auto-revert enabled
+        let is_file_watched = self.buffers.iter().any(|(&buffer_id, state)| {
+            state.buffer.file_path() == Some(&path_buf) && self.auto_revert_enabled_for(buffer_id)
+        });
 
-        if !is_file_open {
-            tracing::trace!("Ignoring file change event for non-open file: {}", path);
+        if !is_file_watched {
+            tracing::trace!("Ignoring file change event for unwatched file: {}", path);
             return false;
         }
 
@@ -543,8 +594,19 @@ impl Editor {
             }
         }
 
+        view.tree_mut()
+            .set_max_visible_children(self.config.file_explorer.max_visible_entries);
+        view.set_sort(self.file_explorer_sort);
+        view.set_show_details(self.file_explorer_details);
+
+        // Node IDs of a previous tree mean nothing in the new one
+        self.dir_loads.clear();
+        self.pending_entry_counts.clear();
+        self.pending_entry_metadata.clear();
+
         self.file_explorer = Some(view);
         self.set_status_message(t!("status.file_explorer_ready").to_string());
+        self.reveal_pending_in_file_explorer();
     }
 
     /// Handle file explorer node toggle completed
@@ -558,6 +620,38 @@ impl Editor {
         self.set_status_message(t!("explorer.refreshed_default").to_string());
     }
 
+    /// Handle a directory entry count computed for the explorer badge
+    pub(super) fn handle_file_explorer_entry_count(
+        &mut self,
+        path: PathBuf,
+        count: usize,
+        modified: Option<SystemTime>,
+    ) {
+        if !self.pending_entry_counts.remove(&path) {
+            return;
+        }
+        if let Some(explorer) = &mut self.file_explorer {
+            explorer.set_entry_count(path, count, modified);
+        }
+    }
+
+    /// Handle metadata fetched for explorer entries listed without it
+    pub(super) fn handle_file_explorer_entry_metadata(
+        &mut self,
+        entries: Vec<(PathBuf, Option<FsMetadata>)>,
+    ) {
+        // An entry whose stat failed gets empty metadata, so it isn't asked
+        // for again
+        let entries: Vec<(PathBuf, FsMetadata)> = entries
+            .into_iter()
+            .filter(|(path, _)| self.pending_entry_metadata.remove(path))
+            .map(|(path, metadata)| (path, metadata.unwrap_or_default()))
+            .collect();
+        if let Some(explorer) = &mut self.file_explorer {
+            explorer.set_entry_metadata(entries);
+        }
+    }
+
     /// Handle file explorer expanded to path
     pub(super) fn handle_file_explorer_expanded_to_path(&mut self, mut view: FileTreeView) {
         tracing::trace!(
@@ -566,6 +660,7 @@ impl Editor {
         view.update_scroll_for_selection();
         self.file_explorer = Some(view);
         self.file_explorer_sync_in_progress = false;
+        self.reveal_pending_in_file_explorer();
     }
 }
 
@@ -591,33 +686,6 @@ impl Editor {
         );
     }
 
-    /// Process TypeScript plugin commands
-    ///
-    /// Returns true if any commands were processed
-    pub(super) fn process_plugin_commands(&mut self) -> bool {
-        let commands = self.plugin_manager.process_commands();
-        if commands.is_empty() {
-            return false;
-        }
-
-        tracing::trace!(
-            "process_plugin_commands: processing {} commands",
-            commands.len()
-        );
-
-        for command in commands {
-            tracing::trace!(
-                "process_plugin_commands: handling command {:?}",
-                std::mem::discriminant(&command)
-            );
-            if let Err(e) = self.handle_plugin_command(command) {
-                tracing::error!("Error handling TypeScript plugin command: {}", e);
-            }
-        }
-
-        true
-    }
-
     /// Process pending plugin action completions
     #[cfg(feature = "plugins")]
     pub(super) fn process_pending_plugin_actions(&mut self) {
@@ -660,17 +728,19 @@ impl Editor {
 
         let restart_results = lsp.process_pending_restarts();
 
-        for (language, success, message) in restart_results {
+        for (label, success, message) in restart_results {
             self.status_message = Some(message.clone());
 
             if success {
-                self.resend_did_open_for_language(&language);
+                self.resend_did_open_for_language(&label);
             }
         }
     }
 
-    /// Re-send didOpen notifications for all buffers of a given language
-    pub(super) fn resend_did_open_for_language(&mut self, language: &str) {
+    /// Re-send didOpen notifications for all buffers of a given language to
+    /// the server with label `label`
+    pub(super) fn resend_did_open_for_language(&mut self, label: &str) {
+        let language = label_language(label);
         // Find all open buffers for this language
         let buffers_for_language: Vec<_> = self
             .buffer_metadata
@@ -695,9 +765,7 @@ impl Editor {
                     Some(c) => c,
                     None => continue, // Skip buffers that aren't fully loaded
                 };
-                let uri: Option<lsp_types::Uri> = url::Url::from_file_path(&path)
-                    .ok()
-                    .and_then(|u| u.as_str().parse::<lsp_types::Uri>().ok());
+                let uri = crate::services::lsp::uri::path_to_uri(&path);
 
                 if let Some(uri) = uri {
                     if let Some(lang_id) = crate::services::lsp::manager::detect_language(
@@ -705,8 +773,14 @@ impl Editor {
                         &self.config.languages,
                     ) {
                         if let Some(lsp) = self.lsp.as_mut() {
-                            if let Some(handle) = lsp.get_or_spawn(&lang_id) {
-                                let _ = handle.did_open(uri, content, lang_id);
+                            if let Some(handle) = lsp.get_or_spawn_server(label) {
+                                if handle.did_open(uri, content, lang_id).is_ok() {
+                                    let handle_id = handle.id();
+                                    if let Some(metadata) = self.buffer_metadata.get_mut(&buffer_id)
+                                    {
+                                        metadata.lsp_opened_with.insert(handle_id);
+                                    }
+                                }
                             }
                         }
                     }
@@ -0,0 +1,304 @@
+//! Completion popup that opens by itself while typing
+//!
+//! With `editor.auto_complete` on, and the language's `auto_complete` not
+//! turned off, typing a word character schedules completion for when typing
+//! pauses for `editor.auto_complete_delay_ms`; typing a trigger (the
+//! language's `completion_trigger_characters` or the ones its language server
+//! reports, e.g. `.`, `::`, `->`) requests it right away. The popup only
+//! opens if there are results, and never for text typed inside a comment or
+//! string when the highlighter can tell those apart.
+//!
+//! An auto-opened popup stays out of the way: Enter and Tab go to the buffer
+//! until the list is navigated with the arrow or page keys, and keys other
+//! than typing, Backspace, navigation and Escape close it and then do what
+//! they would do without it. Typing a word character filters the list in
+//! place; moving back before the trigger position closes it.
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use crossterm::event::{KeyCode, KeyModifiers};
+
+use super::Editor;
+use crate::model::event::BufferId;
+use crate::primitives::highlighter::HighlightCategory;
+use crate::primitives::word_navigation::find_completion_word_start;
+use crate::services::lsp::manager::{detect_language, label_language};
+use crate::services::todo_scanner::knows_comments;
+
+/// Auto-completion state of the editor
+#[derive(Default)]
+pub(crate) struct AutoCompletion {
+    /// Completion waiting for typing to pause
+    due: Option<DueCompletion>,
+    /// The completion popup, or the request for it, was opened by typing
+    popup: Option<AutoPopup>,
+    /// Completion trigger characters reported by language servers, by
+    /// server label
+    server_triggers: HashMap<String, Vec<String>>,
+}
+
+/// Where and when a scheduled completion opens
+struct DueCompletion {
+    buffer_id: BufferId,
+    position: usize,
+    revision: u64,
+    at: Instant,
+}
+
+/// An auto-opened completion popup
+struct AutoPopup {
+    buffer_id: BufferId,
+    /// Start of the word being completed; moving before it closes the popup
+    start: usize,
+    /// Whether the user moved through the list, which hands it Enter and Tab
+    navigated: bool,
+}
+
+/// Characters that make up the words being completed
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+impl Editor {
+    /// Schedule or request completion after `c` was typed into the buffer
+    pub(super) fn auto_complete_after_typing(&mut self, c: char) {
+        self.auto_completion.due = None;
+        let Some(triggers) = self.auto_completion_triggers() else {
+            return;
+        };
+        let state = self.active_state();
+        if state.cursors.count() > 1 {
+            return;
+        }
+        let position = state.cursors.primary().position;
+        let revision = state.buffer.revision();
+        if self.in_comment_or_string(position) {
+            return;
+        }
+
+        if self.typed_trigger(c, position, &triggers) {
+            self.request_auto_completion(position);
+        } else if is_word_char(c) {
+            let delay = Duration::from_millis(self.config.editor.auto_complete_delay_ms);
+            self.auto_completion.due = Some(DueCompletion {
+                buffer_id: self.active_buffer(),
+                position,
+                revision,
+                at: self.time_source.now() + delay,
+            });
+        }
+    }
+
+    /// Request the completion scheduled by typing once typing has paused
+    ///
+    /// Called every tick of the event loop. Returns true if a completion
+    /// was requested.
+    pub fn check_auto_completion_timer(&mut self) -> bool {
+        let Some(due) = &self.auto_completion.due else {
+            return false;
+        };
+        if self.time_source.now() < due.at {
+            return false;
+        }
+        let Some(due) = self.auto_completion.due.take() else {
+            return false;
+        };
+
+        let state = self.active_state();
+        let unchanged = self.active_buffer() == due.buffer_id
+            && state.buffer.revision() == due.revision
+            && state.cursors.primary().position == due.position
+            && !state.popups.is_visible();
+        if !unchanged || self.is_prompting() {
+            return false;
+        }
+        let start = find_completion_word_start(&state.buffer, due.position);
+        self.request_auto_completion(start);
+        true
+    }
+
+    /// Drop any scheduled completion and forget that the popup opened by itself
+    pub(super) fn cancel_auto_completion(&mut self) {
+        self.auto_completion.due = None;
+        self.auto_completion.popup = None;
+    }
+
+    /// Note the completion trigger characters a language server reported
+    pub(super) fn set_server_completion_triggers(
+        &mut self,
+        language: String,
+        characters: Vec<String>,
+    ) {
+        self.auto_completion
+            .server_triggers
+            .insert(language, characters);
+    }
+
+    /// Whether the completion popup on top was opened by typing
+    pub(super) fn is_auto_completion_popup(&self) -> bool {
+        let state = self.active_state();
+        state.popups.is_completion_popup()
+            && self
+                .auto_completion
+                .popup
+                .as_ref()
+                .is_some_and(|popup| popup.buffer_id == self.active_buffer())
+    }
+
+    /// Close an auto-opened completion popup if the key isn't one it uses
+    ///
+    /// Returns true if the popup was closed and the key should be handled as
+    /// if it had not been open.
+    pub(super) fn auto_completion_yields_key(
+        &mut self,
+        code: KeyCode,
+        modifiers: KeyModifiers,
+    ) -> bool {
+        if !self.is_auto_completion_popup() {
+            return false;
+        }
+        let Some(popup) = self.auto_completion.popup.as_mut() else {
+            return false;
+        };
+        let typing = modifiers.difference(KeyModifiers::SHIFT).is_empty();
+        let yields = match code {
+            KeyCode::Up | KeyCode::Down | KeyCode::PageUp | KeyCode::PageDown
+                if modifiers.is_empty() =>
+            {
+                popup.navigated = true;
+                false
+            }
+            KeyCode::BackTab => {
+                popup.navigated = true;
+                false
+            }
+            KeyCode::Enter | KeyCode::Tab if modifiers.is_empty() => !popup.navigated,
+            KeyCode::Esc | KeyCode::Backspace if modifiers.is_empty() => false,
+            KeyCode::Char(c) if typing => !is_word_char(c),
+            _ => true,
+        };
+        if yields {
+            self.dismiss_auto_completion();
+        }
+        yields
+    }
+
+    /// Close an auto-opened completion popup once the cursor moved back
+    /// before the position it was opened for
+    ///
+    /// Returns true if the popup was closed.
+    pub(super) fn dismiss_auto_completion_before_start(&mut self) -> bool {
+        if !self.is_auto_completion_popup() {
+            return false;
+        }
+        let position = self.active_state().cursors.primary().position;
+        if self
+            .auto_completion
+            .popup
+            .as_ref()
+            .is_some_and(|popup| position < popup.start)
+        {
+            self.dismiss_auto_completion();
+            return true;
+        }
+        false
+    }
+
+    fn dismiss_auto_completion(&mut self) {
+        self.hide_popup();
+        self.completion_items = None;
+        self.auto_completion.popup = None;
+    }
+
+    /// Request completion for the word starting at `start`, marking the popup
+    /// as opened by typing
+    fn request_auto_completion(&mut self, start: usize) {
+        self.auto_completion.popup = Some(AutoPopup {
+            buffer_id: self.active_buffer(),
+            start,
+            navigated: false,
+        });
+        if let Err(e) = self.request_completion() {
+            tracing::debug!("Auto-completion request failed: {}", e);
+        }
+    }
+
+    /// Trigger texts for the active buffer, or None if auto-completion is off
+    /// for it
+    fn auto_completion_triggers(&self) -> Option<Vec<String>> {
+        if !self.config.editor.auto_complete {
+            return None;
+        }
+        let language = self
+            .buffer_metadata
+            .get(&self.active_buffer())
+            .and_then(|metadata| metadata.file_path())
+            .and_then(|path| detect_language(path, &self.config.languages));
+        let Some(language) = language else {
+            return Some(Vec::new());
+        };
+        let config = self.config.languages.get(&language);
+        if config.is_some_and(|config| !config.auto_complete) {
+            return None;
+        }
+        let mut triggers = config
+            .map(|config| config.completion_trigger_characters.clone())
+            .unwrap_or_default();
+        for (label, server) in &self.auto_completion.server_triggers {
+            if label_language(label) == language {
+                triggers.extend(server.iter().cloned());
+            }
+        }
+        Some(triggers)
+    }
+
+    /// Whether the text before `position`, ending with the typed `c`, ends
+    /// with one of the triggers
+    fn typed_trigger(&mut self, c: char, position: usize, triggers: &[String]) -> bool {
+        let candidates: Vec<&String> = triggers
+            .iter()
+            .filter(|trigger| trigger.ends_with(c))
+            .collect();
+        let Some(longest) = candidates.iter().map(|trigger| trigger.len()).max() else {
+            return false;
+        };
+        let before = self
+            .active_state_mut()
+            .get_text_range(position.saturating_sub(longest), position);
+        candidates
+            .iter()
+            .any(|trigger| before.ends_with(trigger.as_str()))
+    }
+
+    /// Whether the character before `position` is inside a comment or string
+    ///
+    /// False when the highlighter can't tell comments and strings apart.
+    fn in_comment_or_string(&mut self, position: usize) -> bool {
+        if position == 0 {
+            return false;
+        }
+        let context_bytes = self.config.editor.highlight_context_bytes;
+        let buffer_id = self.active_buffer();
+        let state = self.buffers.get_mut(&buffer_id).unwrap();
+        if !knows_comments(&state.highlighter) {
+            return false;
+        }
+        state
+            .highlighter
+            .highlight_viewport(
+                &state.buffer,
+                position - 1,
+                position,
+                &self.theme,
+                context_bytes,
+            )
+            .iter()
+            .any(|span| {
+                matches!(
+                    span.category,
+                    Some(HighlightCategory::Comment | HighlightCategory::String)
+                ) && span.range.contains(&(position - 1))
+            })
+    }
+}
@@ -0,0 +1,271 @@
+//! Per-buffer control over reloading files that change on disk
+//!
+//! Whether a buffer is reloaded is decided, in order, by:
+//! - follow-tail mode, which always reloads
+//! - the buffer's own auto-revert setting ("Toggle Auto-Revert for Buffer")
+//! - `files.auto_revert_exclude`, whose files are never checked
+//! - the global auto-revert setting (`files.auto_revert`, "Toggle Auto-Revert")
+//!
+//! Follow-tail keeps the cursor and view at the end of the file after every
+//! reload, like `tail -f`, until the user scrolls or moves up.
+
+use super::Editor;
+use crate::config::AutoRevertMode;
+use crate::input::keybindings::Action;
+use crate::model::event::BufferId;
+use crate::view::prompt::PromptType;
+use ignore::gitignore::GitignoreBuilder;
+use rust_i18n::t;
+use std::path::Path;
+
+impl Editor {
+    /// Whether changes on disk to this buffer's file are picked up
+    pub(super) fn auto_revert_enabled_for(&self, buffer_id: BufferId) -> bool {
+        let Some(metadata) = self.buffer_metadata.get(&buffer_id) else {
+            return false;
+        };
+        if metadata.follow_tail {
+            return true;
+        }
+        if let Some(enabled) = metadata.auto_revert {
+            return enabled;
+        }
+        match metadata.file_path() {
+            Some(path) => self.auto_revert_enabled && !self.is_auto_revert_excluded(path),
+            None => false,
+        }
+    }
+
+    /// Follow a change of `files.auto_revert` in the config
+    ///
+    /// Left alone otherwise, so saving unrelated settings keeps the state of
+    /// "Toggle Auto-Revert".
+    pub(super) fn apply_auto_revert_mode(&mut self, old_mode: AutoRevertMode) {
+        if self.config.files.auto_revert != old_mode {
+            self.auto_revert_enabled = self.config.files.auto_revert != AutoRevertMode::Off;
+        }
+    }
+
+    /// Whether a change on disk should be confirmed before reloading
+    ///
+    /// Only buffers following the global setting are asked about.
+    pub(super) fn should_ask_before_revert(&self, buffer_id: BufferId) -> bool {
+        self.config.files.auto_revert == AutoRevertMode::Ask
+            && self
+                .buffer_metadata
+                .get(&buffer_id)
+                .is_some_and(|m| m.auto_revert.is_none() && !m.follow_tail)
+    }
+
+    /// Whether a file matches one of the `files.auto_revert_exclude` globs
+    fn is_auto_revert_excluded(&self, path: &Path) -> bool {
+        let patterns = &self.config.files.auto_revert_exclude;
+        if patterns.is_empty() {
+            return false;
+        }
+
+        let mut builder = GitignoreBuilder::new(&self.working_dir);
+        for pattern in patterns {
+            if let Err(e) = builder.add_line(None, pattern) {
+                tracing::warn!("Invalid auto-revert exclude pattern '{}': {}", pattern, e);
+            }
+        }
+        let matcher = match builder.build() {
+            Ok(matcher) => matcher,
+            Err(e) => {
+                tracing::warn!("Failed to build auto-revert exclude patterns: {}", e);
+                return false;
+            }
+        };
+
+        // Patterns relative to the working directory also match the files in
+        // excluded directories; files elsewhere are matched by name only
+        if path.starts_with(&self.working_dir) {
+            matcher.matched_path_or_any_parents(path, false).is_ignore()
+        } else {
+            matcher.matched(path, false).is_ignore()
+        }
+    }
+
+    /// Toggle auto-revert for the active buffer, overriding the global setting
+    pub fn toggle_buffer_auto_revert(&mut self) {
+        let buffer_id = self.active_buffer();
+        if self
+            .buffer_metadata
+            .get(&buffer_id)
+            .and_then(|m| m.file_path())
+            .is_none()
+        {
+            self.set_status_message(t!("status.no_file_to_revert").to_string());
+            return;
+        }
+
+        let enabled = !self.auto_revert_enabled_for(buffer_id);
+        if let Some(metadata) = self.buffer_metadata.get_mut(&buffer_id) {
+            metadata.auto_revert = Some(enabled);
+            if !enabled {
+                metadata.follow_tail = false;
+            }
+        }
+        // Start from the file as it is now, so only later changes are reloaded
+        if let Some(path) = self
+            .active_state()
+            .buffer
+            .file_path()
+            .map(Path::to_path_buf)
+        {
+            self.watch_file(&path);
+        }
+
+        let message = if enabled {
+            t!("status.buffer_auto_revert_enabled")
+        } else {
+            t!("status.buffer_auto_revert_disabled")
+        };
+        self.set_status_message(message.to_string());
+    }
+
+    /// Toggle follow-tail mode for the active buffer
+    pub fn toggle_follow_tail(&mut self) {
+        let buffer_id = self.active_buffer();
+        let path = self
+            .buffer_metadata
+            .get(&buffer_id)
+            .and_then(|m| m.file_path())
+            .cloned();
+        // The editor log buffer reads its file itself
+        if path.is_none() && !self.is_editor_log_buffer(buffer_id) {
+            self.set_status_message(t!("status.no_file_to_revert").to_string());
+            return;
+        }
+
+        let follow_tail = match self.buffer_metadata.get_mut(&buffer_id) {
+            Some(metadata) => {
+                metadata.follow_tail = !metadata.follow_tail;
+                metadata.follow_tail
+            }
+            None => return,
+        };
+        if follow_tail {
+            if let Some(path) = &path {
+                self.watch_file(path);
+            }
+            self.pin_follow_tail(buffer_id);
+            self.set_status_message(t!("status.follow_tail_enabled").to_string());
+        } else {
+            self.set_status_message(t!("status.follow_tail_disabled").to_string());
+        }
+    }
+
+    /// Move the cursor to the end of the buffer and scroll every split
+    /// showing it so the end is visible
+    pub(super) fn pin_follow_tail(&mut self, buffer_id: BufferId) {
+        let Some(state) = self.buffers.get_mut(&buffer_id) else {
+            return;
+        };
+        let end = state.buffer.len();
+        state.cursors.remove_secondary();
+        state.cursors.primary_mut().move_to(end, false);
+        let cursor = *state.cursors.primary();
+
+        for split_id in self.split_manager.splits_for_buffer(buffer_id) {
+            if let Some(view_state) = self.split_view_states.get_mut(&split_id) {
+                view_state.cursors = state.cursors.clone();
+                view_state.viewport.clear_skip_ensure_visible();
+                view_state
+                    .viewport
+                    .ensure_visible(&mut state.buffer, &cursor);
+            }
+        }
+    }
+
+    /// Leave follow-tail mode because the user scrolled up in the buffer
+    pub(super) fn stop_follow_tail(&mut self, buffer_id: BufferId) {
+        let Some(metadata) = self.buffer_metadata.get_mut(&buffer_id) else {
+            return;
+        };
+        if metadata.follow_tail {
+            metadata.follow_tail = false;
+            self.set_status_message(t!("status.follow_tail_disabled").to_string());
+        }
+    }
+
+    /// Leave follow-tail mode if the action moves back through the active buffer
+    pub(super) fn stop_follow_tail_for_action(&mut self, action: &Action) {
+        if matches!(
+            action,
+            Action::ScrollUp
+                | Action::MoveUp
+                | Action::MovePageUp
+                | Action::MoveDocumentStart
+                | Action::SelectUp
+                | Action::SelectPageUp
+                | Action::SelectDocumentStart
+                | Action::BlockSelectUp
+        ) {
+            self.stop_follow_tail(self.active_buffer());
+        }
+    }
+
+    /// Ask whether to reload a buffer whose file changed on disk
+    pub(super) fn ask_to_reload(&mut self, buffer_id: BufferId) {
+        let Some(name) = self
+            .buffer_metadata
+            .get(&buffer_id)
+            .map(|m| m.display_name.clone())
+        else {
+            return;
+        };
+
+        match self.prompt.as_ref().map(|p| &p.prompt_type) {
+            Some(PromptType::ConfirmReloadChanged { .. }) => {}
+            Some(_) => {
+                // Don't interrupt another prompt; ask again on the next check
+                self.set_status_message(t!("status.file_changed_on_disk", name = name).to_string());
+            }
+            None => {
+                self.start_prompt(
+                    t!(
+                        "prompt.reload_changed",
+                        name = name,
+                        revert_key = t!("prompt.key.revert"),
+                        cancel_key = t!("prompt.key.cancel")
+                    )
+                    .to_string(),
+                    PromptType::ConfirmReloadChanged { buffer_id },
+                );
+            }
+        }
+    }
+
+    /// Answer to "file changed on disk, reload?"
+    pub(super) fn handle_confirm_reload(&mut self, input: &str, buffer_id: BufferId) {
+        let input_lower = input.trim().to_lowercase();
+        let revert_key = t!("prompt.key.revert").to_string().to_lowercase();
+        let path = self
+            .buffers
+            .get(&buffer_id)
+            .and_then(|s| s.buffer.file_path())
+            .map(Path::to_path_buf);
+
+        match path {
+            Some(path) if input_lower == revert_key || input_lower == "reload" => {
+                self.reload_changed_buffer(buffer_id, &path);
+            }
+            _ => self.keep_changed_buffer(buffer_id),
+        }
+    }
+
+    /// Keep a buffer as it is after its file changed on disk, and only ask
+    /// again when the file changes once more
+    pub(super) fn keep_changed_buffer(&mut self, buffer_id: BufferId) {
+        if let Some(path) = self
+            .buffers
+            .get(&buffer_id)
+            .and_then(|s| s.buffer.file_path())
+            .map(Path::to_path_buf)
+        {
+            self.watch_file(&path);
+        }
+    }
+}
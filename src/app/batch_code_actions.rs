@@ -0,0 +1,838 @@
+//! Batch code actions
+//!
+//! "Apply Code Action to Files" asks for a code action kind (offering the
+//! kinds the running language servers advertise, e.g.
+//! `source.organizeImports`) and for the files to run it on: the open
+//! buffers, the files under the working directory matching a glob, or all of
+//! them. Files without a language server are left out.
+//!
+//! Each file's server is asked for the actions of that kind over the whole
+//! file, at most [`MAX_IN_FLIGHT`] files at a time, and the first action
+//! with an edit is taken. A file open in a buffer is asked about as it is in
+//! the buffer; any other file is only opened on the server (didOpen with its
+//! text on disk, didClose once answered), so no buffer is created for it.
+//!
+//! The edits of every file go to the pending edits review. Confirming it
+//! applies them and saves the files, except those whose buffers already had
+//! unsaved edits, which are left unsaved. Cancelling the run's progress
+//! entry stops it: the files answered so far are still reviewed, and the
+//! status says which file it stopped at.
+
+use lsp_types::{CodeActionKind, CodeActionOrCommand, Uri};
+use rust_i18n::t;
+use std::collections::{HashMap, VecDeque};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use super::lsp_rename::{file_pending_edits, workspace_edit_files};
+use super::pending_edits::OnConfirm;
+use super::Editor;
+use crate::input::commands::Suggestion;
+use crate::input::fuzzy::fuzzy_match;
+use crate::model::pending_edits::PendingEdit;
+use crate::model::position_encoding::PositionEncoding;
+use crate::services::lsp::manager::detect_language;
+use crate::services::lsp::uri::path_to_uri;
+use crate::view::notifications::NotificationSeverity;
+use crate::view::progress::{next_progress_id, ProgressOwner};
+use crate::view::prompt::PromptType;
+
+/// Most files waiting for their server's answer at once, so the server's
+/// queue isn't flooded
+const MAX_IN_FLIGHT: usize = 4;
+
+/// How long a file waits for its server to start, and then for its answer
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Files a batch code action runs on
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BatchScope {
+    /// The files open in buffers
+    OpenBuffers,
+    /// The files under the working directory matching a gitignore-style glob
+    Glob(String),
+    /// Every file under the working directory not ignored by git
+    Workspace,
+}
+
+/// A file to run the code action on
+#[derive(Debug, Clone)]
+struct BatchFile {
+    /// Position in the run, for reporting where it stopped
+    index: usize,
+    path: PathBuf,
+    language: String,
+}
+
+/// A file waiting for its server's answer
+struct BatchRequest {
+    file: BatchFile,
+    /// Label of the server asked
+    server: String,
+    /// The text the edits are positioned against
+    content: String,
+    encoding: PositionEncoding,
+    /// The document opened on the server for this request only
+    transient: Option<Uri>,
+    deadline: Instant,
+}
+
+/// A code action running on many files
+pub(crate) struct BatchCodeActionRun {
+    kind: String,
+    /// Files not asked about yet, in order
+    queue: VecDeque<BatchFile>,
+    in_flight: HashMap<u64, BatchRequest>,
+    total: usize,
+    done: usize,
+    /// Edits of the files answered so far
+    edits: Vec<PendingEdit>,
+    changed: usize,
+    skipped: usize,
+    /// Files that failed, with why
+    errors: Vec<(PathBuf, String)>,
+    progress_id: u32,
+    started_at: Instant,
+}
+
+/// Choices matching what was typed, in their order
+fn matching<'a>(choices: &'a [String], input: &str) -> Vec<&'a str> {
+    choices
+        .iter()
+        .filter(|choice| fuzzy_match(input, choice).matched)
+        .map(String::as_str)
+        .collect()
+}
+
+/// Whether scope input is a glob rather than the start of a choice
+fn is_glob(input: &str) -> bool {
+    input.contains(['*', '?', '[', '/', '.'])
+}
+
+/// LSP position of the end of `content`
+fn end_position(content: &str, encoding: PositionEncoding) -> (u32, u32) {
+    let line = content.matches('\n').count();
+    let last_line = &content[content.rfind('\n').map_or(0, |i| i + 1)..];
+    (line as u32, encoding.units(last_line) as u32)
+}
+
+/// The files under `root` not ignored by git, matching `glob` if given
+fn walk_files(root: &Path, glob: Option<&str>) -> Result<Vec<PathBuf>, String> {
+    let matcher = match glob {
+        Some(glob) => {
+            let mut builder = ignore::gitignore::GitignoreBuilder::new(root);
+            builder
+                .add_line(None, glob)
+                .map_err(|e| format!("'{}': {}", glob, e))?;
+            Some(builder.build().map_err(|e| format!("'{}': {}", glob, e))?)
+        }
+        None => None,
+    };
+    let mut files: Vec<PathBuf> = ignore::WalkBuilder::new(root)
+        .require_git(false)
+        .build()
+        .flatten()
+        .filter(|entry| entry.file_type().is_some_and(|t| t.is_file()))
+        .map(|entry| entry.into_path())
+        .filter(|path| {
+            matcher
+                .as_ref()
+                .is_none_or(|matcher| matcher.matched_path_or_any_parents(path, false).is_ignore())
+        })
+        .collect();
+    files.sort();
+    Ok(files)
+}
+
+impl Editor {
+    /// Prompt for the code action kind to run on many files
+    pub(super) fn start_batch_code_action_prompt(&mut self) {
+        if self.batch_code_action.is_some() {
+            self.set_status_message(t!("batch_code_action.in_progress").to_string());
+            return;
+        }
+        self.start_prompt(
+            t!("batch_code_action.kind_prompt").to_string(),
+            PromptType::BatchCodeActionKind,
+        );
+        self.update_prompt_suggestions();
+    }
+
+    /// The code action kinds the initialized servers advertise
+    fn advertised_code_action_kinds(&self) -> Vec<String> {
+        let mut kinds: Vec<String> = self
+            .lsp_code_action_kinds
+            .values()
+            .flatten()
+            .cloned()
+            .collect();
+        kinds.sort();
+        kinds.dedup();
+        kinds
+    }
+
+    /// Labels of the scope choices, with their scopes
+    fn batch_scope_choices() -> Vec<(String, BatchScope)> {
+        vec![
+            (
+                t!("batch_code_action.scope_open").to_string(),
+                BatchScope::OpenBuffers,
+            ),
+            (
+                t!("batch_code_action.scope_workspace").to_string(),
+                BatchScope::Workspace,
+            ),
+        ]
+    }
+
+    /// Offer the kinds, or the scopes, matching what was typed
+    pub(super) fn update_batch_code_action_suggestions(&mut self, input: &str) {
+        let choices = match self.prompt.as_ref().map(|p| &p.prompt_type) {
+            Some(PromptType::BatchCodeActionKind) => self.advertised_code_action_kinds(),
+            Some(PromptType::BatchCodeActionScope { .. }) if is_glob(input) => Vec::new(),
+            Some(PromptType::BatchCodeActionScope { .. }) => Self::batch_scope_choices()
+                .into_iter()
+                .map(|(label, _)| label)
+                .collect(),
+            _ => return,
+        };
+        let suggestions: Vec<Suggestion> = matching(&choices, input)
+            .into_iter()
+            .map(|choice| Suggestion::new(choice.to_string()))
+            .collect();
+        if let Some(prompt) = &mut self.prompt {
+            prompt.selected_suggestion = if suggestions.is_empty() {
+                None
+            } else {
+                Some(0)
+            };
+            prompt.suggestions = suggestions;
+        }
+    }
+
+    /// Take the kind, a highlighted advertised one over what was typed, and
+    /// prompt for the files
+    pub(super) fn handle_batch_code_action_kind(
+        &mut self,
+        input: &str,
+        selected_index: Option<usize>,
+    ) {
+        let kinds = self.advertised_code_action_kinds();
+        let kind = match selected_index {
+            Some(i) if !kinds.iter().any(|kind| kind == input) => matching(&kinds, input)
+                .get(i)
+                .map_or(input, |kind| *kind)
+                .to_string(),
+            _ => input.trim().to_string(),
+        };
+        if kind.is_empty() {
+            return;
+        }
+        self.start_prompt(
+            t!("batch_code_action.scope_prompt", kind = &kind).to_string(),
+            PromptType::BatchCodeActionScope { kind },
+        );
+        self.update_prompt_suggestions();
+    }
+
+    /// Take the highlighted scope, or else what was typed as a glob, and run
+    ///
+    /// Input with `*`, `?`, `[`, `/` or `.` is always a glob, so that `rs`
+    /// isn't taken for "Open buffers".
+    pub(super) fn handle_batch_code_action_scope(
+        &mut self,
+        kind: String,
+        input: &str,
+        selected_index: Option<usize>,
+    ) {
+        let choices = Self::batch_scope_choices();
+        let labels: Vec<String> = choices.iter().map(|(label, _)| label.clone()).collect();
+        let picked = selected_index
+            .filter(|_| !is_glob(input))
+            .and_then(|i| matching(&labels, input).get(i).copied())
+            .and_then(|label| choices.iter().find(|(l, _)| l == label))
+            .map(|(_, scope)| scope.clone());
+        let scope = match picked {
+            Some(scope) => scope,
+            None if input.trim().is_empty() => return,
+            None => BatchScope::Glob(input.trim().to_string()),
+        };
+        self.run_batch_code_action(kind, scope);
+    }
+
+    /// Run the code action `kind` on the files of `scope`
+    pub fn run_batch_code_action(&mut self, kind: String, scope: BatchScope) {
+        if self.batch_code_action.is_some() {
+            self.set_status_message(t!("batch_code_action.in_progress").to_string());
+            return;
+        }
+        let files = match self.batch_files(&scope) {
+            Ok(files) => files,
+            Err(error) => {
+                self.set_status_message(
+                    t!("batch_code_action.invalid_glob", error = error).to_string(),
+                );
+                return;
+            }
+        };
+        if files.is_empty() {
+            self.set_status_message(t!("batch_code_action.no_files", kind = &kind).to_string());
+            return;
+        }
+
+        let total = files.len();
+        let progress_id = next_progress_id();
+        self.begin_progress(
+            progress_id,
+            ProgressOwner::Editor,
+            t!("batch_code_action.progress_title", kind = &kind).to_string(),
+            true,
+        );
+        self.batch_code_action = Some(BatchCodeActionRun {
+            kind,
+            queue: files.into(),
+            in_flight: HashMap::new(),
+            total,
+            done: 0,
+            edits: Vec::new(),
+            changed: 0,
+            skipped: 0,
+            errors: Vec::new(),
+            progress_id,
+            started_at: self.time_source.now(),
+        });
+        self.update_batch_code_action_progress();
+        self.continue_batch_code_action();
+    }
+
+    /// Whether a batch code action is running
+    pub fn is_running_batch_code_action(&self) -> bool {
+        self.batch_code_action.is_some()
+    }
+
+    /// Files answered and files in all of the running batch code action
+    pub fn batch_code_action_progress(&self) -> Option<(usize, usize)> {
+        self.batch_code_action
+            .as_ref()
+            .map(|run| (run.done, run.total))
+    }
+
+    /// The files of `scope` that have a language server, in order
+    fn batch_files(&self, scope: &BatchScope) -> Result<Vec<BatchFile>, String> {
+        let paths = match scope {
+            BatchScope::OpenBuffers => {
+                let mut paths: Vec<PathBuf> = self
+                    .buffer_metadata
+                    .values()
+                    .filter(|metadata| metadata.lsp_enabled)
+                    .filter_map(|metadata| metadata.file_path().cloned())
+                    .collect();
+                paths.sort();
+                paths.dedup();
+                paths
+            }
+            BatchScope::Glob(glob) => walk_files(&self.working_dir, Some(glob))?,
+            BatchScope::Workspace => walk_files(&self.working_dir, None)?,
+        };
+        let files = paths
+            .into_iter()
+            .filter_map(|path| {
+                let language = detect_language(&path, &self.config.languages)?;
+                self.config
+                    .lsp
+                    .get(&language)
+                    .is_some_and(|config| config.any_enabled())
+                    .then(|| (self.resolve_pending_edit_path(&path), language))
+            })
+            .enumerate()
+            .map(|(index, (path, language))| BatchFile {
+                index,
+                path,
+                language,
+            })
+            .collect();
+        Ok(files)
+    }
+
+    /// Ask about queued files whose server is ready, up to the limit, and
+    /// finish once every file is answered
+    fn continue_batch_code_action(&mut self) {
+        loop {
+            let Some(run) = self.batch_code_action.as_ref() else {
+                return;
+            };
+            if run.queue.is_empty() && run.in_flight.is_empty() {
+                self.finish_batch_code_action(false);
+                return;
+            }
+            if run.in_flight.len() >= MAX_IN_FLIGHT {
+                return;
+            }
+            let Some(position) = run
+                .queue
+                .iter()
+                .position(|file| self.is_lsp_server_ready(&file.language))
+            else {
+                self.spawn_batch_servers();
+                return;
+            };
+            let kind = run.kind.clone();
+            let Some(file) = self
+                .batch_code_action
+                .as_mut()
+                .and_then(|run| run.queue.remove(position))
+            else {
+                return;
+            };
+            match self.request_batch_code_action(&file, &kind) {
+                Ok(Some((request_id, request))) => {
+                    if let Some(run) = self.batch_code_action.as_mut() {
+                        run.in_flight.insert(request_id, request);
+                    }
+                }
+                Ok(None) => self.record_batch_file(&file.path, Ok(Vec::new())),
+                Err(error) => self.record_batch_file(&file.path, Err(error)),
+            }
+        }
+    }
+
+    /// Start the servers of the queued files, skipping files whose server
+    /// can't be started
+    fn spawn_batch_servers(&mut self) {
+        let Some(run) = self.batch_code_action.as_ref() else {
+            return;
+        };
+        let mut languages: Vec<String> = run.queue.iter().map(|f| f.language.clone()).collect();
+        languages.sort();
+        languages.dedup();
+        for language in languages {
+            if self.is_lsp_server_ready(&language) {
+                continue;
+            }
+            let spawned = self
+                .lsp
+                .as_mut()
+                .and_then(|lsp| lsp.get_or_spawn(&language))
+                .is_some();
+            if spawned {
+                continue;
+            }
+            tracing::info!("No language server for {} to run code actions", language);
+            let Some(run) = self.batch_code_action.as_mut() else {
+                return;
+            };
+            let (unserved, queue): (VecDeque<BatchFile>, VecDeque<BatchFile>) = run
+                .queue
+                .drain(..)
+                .partition(|file| file.language == language);
+            run.queue = queue;
+            for file in unserved {
+                self.record_batch_file(&file.path, Ok(Vec::new()));
+            }
+        }
+    }
+
+    /// Ask the server of `file` for its code actions of `kind`
+    ///
+    /// Returns None if the file is skipped without asking.
+    fn request_batch_code_action(
+        &mut self,
+        file: &BatchFile,
+        kind: &str,
+    ) -> Result<Option<(u64, BatchRequest)>, String> {
+        let request_id = self.next_lsp_request_id;
+        let only = Some(vec![CodeActionKind::from(kind.to_string())]);
+        let Some(server) = self.code_action_server(&file.language, kind) else {
+            return Ok(None);
+        };
+        let buffer_id = self
+            .buffer_metadata
+            .iter()
+            .find(|(_, metadata)| metadata.file_path() == Some(&file.path))
+            .map(|(id, _)| *id);
+
+        let (content, encoding, transient) = match buffer_id {
+            Some(buffer_id) => {
+                let content = self
+                    .buffers
+                    .get(&buffer_id)
+                    .and_then(|state| state.buffer.to_string())
+                    .ok_or_else(|| t!("batch_code_action.not_loaded").to_string())?;
+                let encoding = self.lsp_position_encoding(buffer_id);
+                let (end_line, end_char) = end_position(&content, encoding);
+                let sent = self
+                    .with_lsp_server_for_buffer(buffer_id, &server, |handle, uri, _language| {
+                        handle
+                            .code_actions(
+                                request_id,
+                                uri.clone(),
+                                0,
+                                0,
+                                end_line,
+                                end_char,
+                                Vec::new(),
+                                only,
+                            )
+                            .is_ok()
+                    })
+                    .unwrap_or(false);
+                if !sent {
+                    return Ok(None);
+                }
+                (content, encoding, None)
+            }
+            None => {
+                let content = std::fs::read_to_string(&file.path).map_err(|e| e.to_string())?;
+                let uri = path_to_uri(&file.path)
+                    .ok_or_else(|| t!("batch_code_action.no_uri").to_string())?;
+                let encoding = self
+                    .lsp_server_sync(&server)
+                    .map(|sync| sync.encoding)
+                    .unwrap_or_default();
+                let (end_line, end_char) = end_position(&content, encoding);
+                let Some(handle) = self
+                    .lsp
+                    .as_mut()
+                    .and_then(|lsp| lsp.get_or_spawn_server(&server))
+                else {
+                    return Ok(None);
+                };
+                handle.did_open(uri.clone(), content.clone(), file.language.clone())?;
+                let sent = handle.code_actions(
+                    request_id,
+                    uri.clone(),
+                    0,
+                    0,
+                    end_line,
+                    end_char,
+                    Vec::new(),
+                    only,
+                );
+                if let Err(error) = sent {
+                    let _ = handle.did_close(uri);
+                    return Err(error);
+                }
+                (content, encoding, Some(uri))
+            }
+        };
+        self.next_lsp_request_id += 1;
+        let request = BatchRequest {
+            file: file.clone(),
+            server,
+            content,
+            encoding,
+            transient,
+            deadline: self.time_source.now() + REQUEST_TIMEOUT,
+        };
+        Ok(Some((request_id, request)))
+    }
+
+    /// Whether an LSP response is for a file of the batch code action
+    pub(super) fn is_batch_code_action_request(&self, request_id: u64) -> bool {
+        self.batch_code_action
+            .as_ref()
+            .is_some_and(|run| run.in_flight.contains_key(&request_id))
+    }
+
+    /// Take the edits of the first action with any for the file asked about
+    pub(super) fn handle_batch_code_action_response(
+        &mut self,
+        request_id: u64,
+        actions: Vec<CodeActionOrCommand>,
+    ) {
+        let Some(request) = self
+            .batch_code_action
+            .as_mut()
+            .and_then(|run| run.in_flight.remove(&request_id))
+        else {
+            return;
+        };
+        self.close_batch_document(&request);
+
+        let edit = actions.into_iter().find_map(|action| match action {
+            CodeActionOrCommand::CodeAction(action) => action.edit,
+            CodeActionOrCommand::Command(_) => None,
+        });
+        let path = &request.file.path;
+        let result = match edit {
+            None => Ok(Vec::new()),
+            Some(edit) => workspace_edit_files(edit).and_then(|files| {
+                let mut edits = Vec::new();
+                for (file, text_edits) in files {
+                    if self.resolve_pending_edit_path(&file) != *path {
+                        tracing::debug!(
+                            "Ignoring code action edits to {:?} asked for {:?}",
+                            file,
+                            path
+                        );
+                        continue;
+                    }
+                    edits.extend(file_pending_edits(
+                        path,
+                        &request.content,
+                        text_edits,
+                        request.encoding,
+                    )?);
+                }
+                Ok(edits)
+            }),
+        };
+        self.record_batch_file(path, result);
+        self.continue_batch_code_action();
+    }
+
+    /// Close the document opened on the server for a request, unless a
+    /// buffer was opened on the file since
+    fn close_batch_document(&mut self, request: &BatchRequest) {
+        let Some(uri) = &request.transient else {
+            return;
+        };
+        let path = &request.file.path;
+        if self
+            .buffer_metadata
+            .values()
+            .any(|metadata| metadata.file_path() == Some(path))
+        {
+            return;
+        }
+        if let Some(handle) = self
+            .lsp
+            .as_mut()
+            .and_then(|lsp| lsp.get_or_spawn_server(&request.server))
+        {
+            if let Err(e) = handle.did_close(uri.clone()) {
+                tracing::warn!("Failed to close {:?} on the server: {}", path, e);
+            }
+        }
+    }
+
+    /// Count a file as answered, with its edits or why it failed
+    fn record_batch_file(&mut self, path: &Path, result: Result<Vec<PendingEdit>, String>) {
+        let Some(run) = self.batch_code_action.as_mut() else {
+            return;
+        };
+        run.done += 1;
+        match result {
+            Ok(edits) if edits.is_empty() => run.skipped += 1,
+            Ok(edits) => {
+                run.changed += 1;
+                run.edits.extend(edits);
+            }
+            Err(error) => {
+                tracing::warn!("Code action {} failed on {:?}: {}", run.kind, path, error);
+                run.errors.push((path.to_path_buf(), error));
+            }
+        }
+        self.update_batch_code_action_progress();
+    }
+
+    fn update_batch_code_action_progress(&mut self) {
+        let Some(run) = self.batch_code_action.as_ref() else {
+            return;
+        };
+        let percentage = (run.done * 100 / run.total.max(1)) as u8;
+        let message = t!(
+            "batch_code_action.progress",
+            done = run.done,
+            total = run.total
+        )
+        .to_string();
+        self.update_progress(run.progress_id, Some(percentage), Some(message));
+    }
+
+    /// Stop a cancelled run, fail files whose server didn't answer or start
+    /// in time, and ask about more files once their server is ready
+    ///
+    /// Returns true if anything visible changed.
+    pub(super) fn poll_batch_code_action(&mut self) -> bool {
+        let Some(run) = self.batch_code_action.as_ref() else {
+            return false;
+        };
+        if self
+            .progress
+            .get(run.progress_id)
+            .is_some_and(|entry| entry.cancelling)
+        {
+            self.finish_batch_code_action(true);
+            return true;
+        }
+        let done = run.done;
+
+        let now = self.time_source.now();
+        let timed_out: Vec<u64> = run
+            .in_flight
+            .iter()
+            .filter(|(_, request)| now >= request.deadline)
+            .map(|(id, _)| *id)
+            .collect();
+        let unstarted: Vec<usize> = if now >= run.started_at + REQUEST_TIMEOUT {
+            run.queue
+                .iter()
+                .filter(|file| !self.is_lsp_server_ready(&file.language))
+                .map(|file| file.index)
+                .collect()
+        } else {
+            Vec::new()
+        };
+        for request_id in timed_out {
+            let Some(request) = self
+                .batch_code_action
+                .as_mut()
+                .and_then(|run| run.in_flight.remove(&request_id))
+            else {
+                continue;
+            };
+            self.cancel_batch_request(request_id, &request);
+            self.record_batch_file(
+                &request.file.path,
+                Err(t!("batch_code_action.timed_out").to_string()),
+            );
+        }
+        for index in unstarted {
+            let Some(file) = self.batch_code_action.as_mut().and_then(|run| {
+                let position = run.queue.iter().position(|f| f.index == index)?;
+                run.queue.remove(position)
+            }) else {
+                continue;
+            };
+            self.record_batch_file(
+                &file.path,
+                Err(t!("batch_code_action.server_not_ready").to_string()),
+            );
+        }
+        self.continue_batch_code_action();
+        self.batch_code_action
+            .as_ref()
+            .is_none_or(|run| run.done != done)
+    }
+
+    /// Tell the server a request's answer is no longer wanted
+    fn cancel_batch_request(&mut self, request_id: u64, request: &BatchRequest) {
+        if let Some(handle) = self
+            .lsp
+            .as_mut()
+            .and_then(|lsp| lsp.get_or_spawn_server(&request.server))
+        {
+            let _ = handle.cancel_request(request_id);
+        }
+        self.close_batch_document(request);
+    }
+
+    /// End the run, reviewing the edits collected so far
+    fn finish_batch_code_action(&mut self, cancelled: bool) {
+        let Some(mut run) = self.batch_code_action.take() else {
+            return;
+        };
+        self.finish_progress(run.progress_id);
+
+        // The first file not answered is where a cancelled run stopped
+        let mut unanswered: Vec<BatchFile> = run.queue.drain(..).collect();
+        for (request_id, request) in std::mem::take(&mut run.in_flight) {
+            self.cancel_batch_request(request_id, &request);
+            unanswered.push(request.file);
+        }
+        let stopped_at = unanswered
+            .into_iter()
+            .min_by_key(|file| file.index)
+            .map(|file| {
+                file.path
+                    .strip_prefix(&self.working_dir)
+                    .unwrap_or(&file.path)
+                    .display()
+                    .to_string()
+            });
+
+        let summary = t!(
+            "batch_code_action.summary",
+            kind = &run.kind,
+            changed = run.changed,
+            skipped = run.skipped,
+            errored = run.errors.len()
+        )
+        .to_string();
+        let message = match stopped_at.filter(|_| cancelled) {
+            Some(file) => t!(
+                "batch_code_action.stopped",
+                summary = summary,
+                file = file,
+                done = run.done,
+                total = run.total
+            )
+            .to_string(),
+            None => summary,
+        };
+
+        if let Some((path, error)) = run.errors.first() {
+            let shown = path.strip_prefix(&self.working_dir).unwrap_or(path);
+            self.notify(
+                NotificationSeverity::Warning,
+                t!(
+                    "batch_code_action.failed",
+                    kind = &run.kind,
+                    file = shown.display().to_string(),
+                    error = error,
+                    count = run.errors.len()
+                )
+                .to_string(),
+            );
+        }
+        if !run.edits.is_empty() {
+            let title = t!(
+                "batch_code_action.review_title",
+                kind = &run.kind,
+                files = run.changed
+            )
+            .to_string();
+            self.start_pending_edits_review(title, run.edits, None, OnConfirm::ApplyAndSave);
+        }
+        self.set_status_message(message);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_end_position() {
+        assert_eq!(end_position("", PositionEncoding::Utf16), (0, 0));
+        assert_eq!(end_position("a\nbc\n", PositionEncoding::Utf16), (2, 0));
+        assert_eq!(end_position("a\n😀é", PositionEncoding::Utf16), (1, 3));
+        assert_eq!(end_position("a\n😀é", PositionEncoding::Utf8), (1, 6));
+    }
+
+    #[test]
+    fn test_walk_files_with_glob() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let root = dir.path();
+        for file in ["a.rs", "b.txt", "src/c.rs", "src/deep/d.rs", "target/e.rs"] {
+            let path = root.join(file);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, "").unwrap();
+        }
+        std::fs::write(root.join(".gitignore"), "target/\n").unwrap();
+
+        let relative = |glob| -> Vec<String> {
+            walk_files(root, glob)
+                .unwrap()
+                .iter()
+                .map(|p| {
+                    p.strip_prefix(root)
+                        .unwrap()
+                        .to_string_lossy()
+                        .replace('\\', "/")
+                })
+                .collect()
+        };
+        assert_eq!(
+            relative(Some("*.rs")),
+            vec!["a.rs", "src/c.rs", "src/deep/d.rs"]
+        );
+        assert_eq!(relative(Some("src/")), vec!["src/c.rs", "src/deep/d.rs"]);
+        assert_eq!(relative(Some("/src/*.rs")), vec!["src/c.rs"]);
+        assert_eq!(
+            relative(None),
+            vec!["a.rs", "b.txt", "src/c.rs", "src/deep/d.rs"]
+        );
+    }
+}
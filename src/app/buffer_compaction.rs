@@ -0,0 +1,327 @@
+//! Compacting the memory of long-lived buffers
+//!
+//! "Compact Buffer" and "Compact All Buffers" drop the undo history (or all
+//! but its last steps), copy the text of unmodified buffers into one fresh
+//! allocation, releasing text only old edits referred to, and give back the
+//! capacity decoration stores kept from entries since removed. With
+//! `performance.auto_compact` on, buffers left untouched for
+//! `auto_compact_idle_minutes` get the same treatment, keeping
+//! `auto_compact_undo_depth` undo steps. "Buffer Memory Report" lists what
+//! each buffer takes.
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use rust_i18n::t;
+use serde_json::json;
+
+use super::file_open::format_size;
+use super::Editor;
+use crate::model::event::BufferId;
+use crate::primitives::text_property::TextPropertyEntry;
+use crate::view::notifications::NotificationSeverity;
+use crate::view::prompt::PromptType;
+
+/// Time between two looks for idle buffers to compact
+const CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Name of the report buffer, reused while it is open
+const MEMORY_REPORT_BUFFER_NAME: &str = "*Buffer Memory*";
+
+/// When each buffer was last touched, for the automatic compaction
+#[derive(Debug, Default)]
+pub(super) struct BufferCompactionState {
+    activity: HashMap<BufferId, Activity>,
+    last_check: Option<Instant>,
+}
+
+#[derive(Debug)]
+struct Activity {
+    /// Event log position when last checked; any edit or cursor movement
+    /// moves it
+    log_index: usize,
+    /// When `log_index` was first seen
+    since: Instant,
+    /// Compacted since the last change
+    compacted: bool,
+}
+
+/// Estimated memory a buffer takes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BufferMemory {
+    pub buffer_id: BufferId,
+    /// Text storage, including text only undo steps still refer to
+    pub text: usize,
+    /// Undo and redo steps
+    pub undo: usize,
+    /// Overlays, virtual text, text properties and markers
+    pub decorations: usize,
+}
+
+impl BufferMemory {
+    pub fn total(&self) -> usize {
+        self.text + self.undo + self.decorations
+    }
+}
+
+impl Editor {
+    /// Estimated memory of a buffer's text, undo history and decorations
+    pub fn buffer_memory(&self, buffer_id: BufferId) -> Option<BufferMemory> {
+        let state = self.buffers.get(&buffer_id)?;
+        Some(BufferMemory {
+            buffer_id,
+            text: state.buffer.memory_bytes(),
+            undo: self
+                .event_logs
+                .get(&buffer_id)
+                .map_or(0, |log| log.memory_bytes()),
+            decorations: state.overlays.memory_bytes()
+                + state.virtual_texts.memory_bytes()
+                + state.text_properties.memory_bytes()
+                + state.marker_list.memory_bytes(),
+        })
+    }
+
+    /// Whether a save, format or save action run holds on to positions in
+    /// the buffer's history or to its current text
+    fn is_buffer_busy(&self, buffer_id: BufferId) -> bool {
+        self.is_buffer_saving(buffer_id)
+            || self.is_buffer_formatting(buffer_id)
+            || self.is_running_save_actions(buffer_id)
+    }
+
+    /// Drop the buffer's undo history, or all but its last `keep` undo
+    /// steps, then compact its text and decorations
+    ///
+    /// Returns the bytes reclaimed, or None for a missing or busy buffer.
+    /// Neither the content nor the cursors change.
+    pub fn compact_buffer(&mut self, buffer_id: BufferId, keep: Option<usize>) -> Option<usize> {
+        if self.is_buffer_busy(buffer_id) {
+            return None;
+        }
+        let before = self.buffer_memory(buffer_id)?.total();
+        let retained = match self.event_logs.get_mut(&buffer_id) {
+            Some(log) => {
+                match keep {
+                    Some(keep) => log.truncate_history(keep),
+                    None => log.discard_history(),
+                };
+                log.piece_trees()
+            }
+            None => Vec::new(),
+        };
+        let state = self.buffers.get_mut(&buffer_id)?;
+        state.buffer.compact(&retained);
+        state.overlays.shrink_to_fit();
+        state.virtual_texts.shrink_to_fit();
+        state.text_properties.shrink_to_fit();
+        state.marker_list.shrink_to_fit();
+        let after = self.buffer_memory(buffer_id)?.total();
+        Some(before.saturating_sub(after))
+    }
+
+    /// Ask whether to drop the undo history of the active buffer, or of all
+    /// buffers, or how many undo steps to keep
+    pub fn start_compact_buffers_prompt(&mut self, all: bool) {
+        let message = if all {
+            t!("compaction.prompt_all")
+        } else {
+            t!("compaction.prompt")
+        };
+        self.start_prompt(message.to_string(), PromptType::CompactBuffers { all });
+    }
+
+    /// Answer to the compaction prompt: "d" drops the whole history, a
+    /// number keeps that many undo steps
+    pub(super) fn handle_compact_buffers_prompt(&mut self, input: &str, all: bool) {
+        let input = input.trim().to_lowercase();
+        let keep = if input == "d" || input == "discard" {
+            None
+        } else if let Ok(steps) = input.parse::<usize>() {
+            Some(steps)
+        } else {
+            self.set_status_message(t!("compaction.cancelled").to_string());
+            return;
+        };
+        let buffer_ids: Vec<BufferId> = if all {
+            let mut ids: Vec<_> = self.buffers.keys().copied().collect();
+            ids.sort_by_key(|id| id.0);
+            ids
+        } else {
+            vec![self.active_buffer()]
+        };
+
+        let mut reclaimed = 0;
+        let mut compacted = 0;
+        let mut skipped = 0;
+        for buffer_id in buffer_ids {
+            match self.compact_buffer(buffer_id, keep) {
+                Some(bytes) => {
+                    reclaimed += bytes;
+                    compacted += 1;
+                }
+                None => skipped += 1,
+            }
+        }
+        let mut message = t!(
+            "compaction.done",
+            count = compacted,
+            size = format_size(reclaimed as u64)
+        )
+        .to_string();
+        if skipped > 0 {
+            message.push_str(&t!("compaction.skipped_busy", count = skipped));
+        }
+        self.notify(NotificationSeverity::Info, message);
+    }
+
+    /// Compact the undo history of buffers left untouched for
+    /// `performance.auto_compact_idle_minutes`, at most once per
+    /// [`CHECK_INTERVAL`]
+    pub(super) fn check_auto_compaction(&mut self) {
+        if !self.config.performance.auto_compact {
+            return;
+        }
+        let now = self.time_source.now();
+        let compaction = &mut self.buffer_compaction;
+        if let Some(last) = compaction.last_check {
+            if now.saturating_duration_since(last) < CHECK_INTERVAL {
+                return;
+            }
+        }
+        compaction.last_check = Some(now);
+
+        let idle_after =
+            Duration::from_secs(self.config.performance.auto_compact_idle_minutes * 60);
+        let buffers = &self.buffers;
+        compaction.activity.retain(|id, _| buffers.contains_key(id));
+        let mut idle = Vec::new();
+        for (buffer_id, log) in &self.event_logs {
+            let log_index = log.current_index();
+            let activity = compaction
+                .activity
+                .entry(*buffer_id)
+                .or_insert_with(|| Activity {
+                    log_index,
+                    since: now,
+                    compacted: false,
+                });
+            if activity.log_index != log_index {
+                *activity = Activity {
+                    log_index,
+                    since: now,
+                    compacted: false,
+                };
+            } else if !activity.compacted
+                && now.saturating_duration_since(activity.since) >= idle_after
+            {
+                idle.push(*buffer_id);
+            }
+        }
+
+        let keep = self.config.performance.auto_compact_undo_depth;
+        for buffer_id in idle {
+            if let Some(bytes) = self.compact_buffer(buffer_id, Some(keep)) {
+                tracing::debug!(
+                    "Compacted idle buffer {:?}, reclaiming {} bytes",
+                    buffer_id,
+                    bytes
+                );
+                if let Some(activity) = self.buffer_compaction.activity.get_mut(&buffer_id) {
+                    activity.compacted = true;
+                }
+            }
+        }
+    }
+
+    /// Show the memory each buffer takes in a read-only buffer
+    pub fn show_buffer_memory_report(&mut self) {
+        let existing = self
+            .buffer_metadata
+            .iter()
+            .find(|(_, m)| m.display_name == MEMORY_REPORT_BUFFER_NAME)
+            .map(|(id, _)| *id);
+        let buffer_id = existing.unwrap_or_else(|| {
+            let buffer_id = self.create_virtual_buffer(
+                MEMORY_REPORT_BUFFER_NAME.to_string(),
+                "special".to_string(),
+                true,
+            );
+            if let Some(state) = self.buffers.get_mut(&buffer_id) {
+                state.editing_disabled = true;
+                state.margins.set_line_numbers(false);
+            }
+            buffer_id
+        });
+
+        let entries = self.buffer_memory_report(buffer_id);
+        if let Err(e) = self.set_virtual_buffer_content(buffer_id, entries) {
+            tracing::error!("Failed to render the buffer memory report: {}", e);
+        }
+        self.set_active_buffer(buffer_id);
+    }
+
+    /// Lines of the memory report, largest buffers first, leaving out the
+    /// report itself
+    fn buffer_memory_report(&self, report_id: BufferId) -> Vec<TextPropertyEntry> {
+        let mut rows: Vec<BufferMemory> = self
+            .buffers
+            .keys()
+            .filter(|id| **id != report_id)
+            .filter_map(|id| self.buffer_memory(*id))
+            .collect();
+        rows.sort_by_key(|row| (std::cmp::Reverse(row.total()), row.buffer_id.0));
+
+        let header = |text: String| {
+            TextPropertyEntry::text(format!("{}\n", text)).with_property("type", json!("header"))
+        };
+        let row = |text: &str, undo: &str, decorations: &str, total: &str, name: &str| {
+            format!(
+                "  {:>10}  {:>10}  {:>11}  {:>10}  {}\n",
+                text, undo, decorations, total, name
+            )
+        };
+        let mut entries = vec![
+            header(t!("compaction.report_title").to_string()),
+            TextPropertyEntry::text("\n"),
+            header(
+                row(
+                    &t!("compaction.report_text"),
+                    &t!("compaction.report_undo"),
+                    &t!("compaction.report_decorations"),
+                    &t!("compaction.report_total"),
+                    &t!("compaction.report_buffer"),
+                )
+                .trim_end()
+                .to_string(),
+            ),
+        ];
+        let size = |bytes: usize| format_size(bytes as u64);
+        for memory in &rows {
+            entries.push(
+                TextPropertyEntry::text(row(
+                    &size(memory.text),
+                    &size(memory.undo),
+                    &size(memory.decorations),
+                    &size(memory.total()),
+                    &self.get_buffer_display_name(memory.buffer_id),
+                ))
+                .with_property("buffer_id", json!(memory.buffer_id.0)),
+            );
+        }
+        let sum = |field: fn(&BufferMemory) -> usize| rows.iter().map(field).sum::<usize>();
+        entries.push(TextPropertyEntry::text(row(
+            &size(sum(|m| m.text)),
+            &size(sum(|m| m.undo)),
+            &size(sum(|m| m.decorations)),
+            &size(sum(BufferMemory::total)),
+            &t!("compaction.report_all"),
+        )));
+        entries.push(TextPropertyEntry::text("\n"));
+        entries.push(TextPropertyEntry::text(format!(
+            "{}\n",
+            t!("compaction.report_hint")
+        )));
+        entries
+    }
+}
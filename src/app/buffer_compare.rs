@@ -0,0 +1,746 @@
+//! "Compare Buffer With…": a side-by-side diff of two open buffers
+//!
+//! The active buffer stays on the left and the buffer picked is shown in a
+//! new split on the right. Changed lines are highlighted on both sides and
+//! the two splits scroll together, line for line. The comparison works on
+//! the buffers' contents rather than files, so unsaved changes, scratch and
+//! untitled buffers compare just as well; the hunks are recomputed once
+//! neither buffer has been edited for [`RECOMPUTE_DELAY`].
+//!
+//! "Next/Previous Hunk" move between hunks in either split, and "Copy Hunk
+//! to Left/Right" replace the hunk at the cursor on one side with the
+//! other side's lines, as one undoable edit of that buffer. Closing either
+//! buffer, or the right split, ends the comparison.
+//!
+//! "Compare With Clipboard" compares the selection, or the whole active
+//! buffer, with the clipboard's text. The text goes into a read-only scratch
+//! buffer on the right, so hunks can only be copied into the real buffer;
+//! the scratch buffer is closed with the comparison unless it was saved.
+
+use std::ops::Range;
+use std::time::{Duration, Instant};
+
+use rust_i18n::t;
+
+use super::revert_buffer::{diff_hunks, line_starts, Hunk};
+use super::{BufferMetadata, Editor};
+use crate::input::commands::Suggestion;
+use crate::model::buffer::Buffer;
+use crate::model::event::{BufferId, Event, EventLog, SplitDirection, SplitId};
+use crate::model::line_diff::matching_lines;
+use crate::model::marker::MarkerId;
+use crate::services::clipboard::SystemClipboardState;
+use crate::state::EditorState;
+use crate::view::notifications::NotificationSeverity;
+use crate::view::overlay::{Overlay, OverlayFace, OverlayNamespace};
+use crate::view::prompt::PromptType;
+use crate::view::scroll_sync::{ScrollSyncGroupId, SyncAnchor};
+use crate::view::split::SplitViewState;
+use crate::view::theme::{DecorationColor, ThemedStyle};
+
+/// Overlay namespace of the changed lines
+const COMPARE_NAMESPACE: &str = "buffer-compare";
+
+/// How long both buffers must go unedited before the hunks are recomputed
+const RECOMPUTE_DELAY: Duration = Duration::from_millis(200);
+
+/// The comparison being shown
+pub(super) struct BufferComparison {
+    left: BufferId,
+    right: BufferId,
+    left_split: SplitId,
+    right_split: SplitId,
+    scroll_group: ScrollSyncGroupId,
+    /// Start and end markers of the part of the left buffer compared, when
+    /// it isn't the whole buffer
+    left_region: Option<(MarkerId, MarkerId)>,
+    /// The right buffer, when it is a scratch buffer to close with the
+    /// comparison
+    scratch: Option<BufferId>,
+    /// Hunks turning the left buffer into the right one
+    hunks: Vec<Hunk>,
+    /// Revisions of the two buffers the hunks were computed from
+    computed: (u64, u64),
+    /// Latest revisions seen, and when they were first seen
+    seen: (u64, u64),
+    seen_at: Instant,
+}
+
+/// Side of the comparison
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Side {
+    Left,
+    Right,
+}
+
+/// Lines of `hunk` on `side`
+fn hunk_lines(hunk: &Hunk, side: Side) -> &Range<usize> {
+    match side {
+        Side::Left => &hunk.old_lines,
+        Side::Right => &hunk.new_lines,
+    }
+}
+
+/// Index of the hunk at `line` on `side`: the one covering the line, or one
+/// with no lines on that side that would be inserted before it
+fn hunk_at_line(hunks: &[Hunk], side: Side, line: usize) -> Option<usize> {
+    hunks.iter().position(|hunk| {
+        let lines = hunk_lines(hunk, side);
+        lines.contains(&line) || (lines.is_empty() && lines.start == line)
+    })
+}
+
+/// First line on `side` of the closest hunk after (or before) `line`
+fn next_hunk_line(hunks: &[Hunk], side: Side, line: usize, forward: bool) -> Option<usize> {
+    let mut starts = hunks.iter().map(|hunk| hunk_lines(hunk, side).start);
+    if forward {
+        starts.find(|&start| start > line)
+    } else {
+        starts.rev().find(|&start| start < line)
+    }
+}
+
+/// Scroll sync anchors where the compared text starts on the left, and at
+/// the start and end of every hunk
+fn sync_anchors(hunks: &[Hunk], left_start_line: usize) -> Vec<SyncAnchor> {
+    let mut anchors = vec![SyncAnchor {
+        left_line: left_start_line,
+        right_line: 0,
+    }];
+    for hunk in hunks {
+        anchors.push(SyncAnchor {
+            left_line: hunk.old_lines.start,
+            right_line: hunk.new_lines.start,
+        });
+        anchors.push(SyncAnchor {
+            left_line: hunk.old_lines.end,
+            right_line: hunk.new_lines.end,
+        });
+    }
+    anchors
+}
+
+impl Editor {
+    /// Prompt for the open buffer to compare the active one with
+    pub fn start_compare_buffer_prompt(&mut self) {
+        let active = self.active_buffer();
+        let mut others: Vec<_> = self
+            .buffer_metadata
+            .iter()
+            .filter(|(id, _)| **id != active && self.buffers.contains_key(id))
+            .map(|(id, metadata)| (*id, metadata.display_name.clone()))
+            .collect();
+        if others.is_empty() {
+            self.set_status_message(t!("compare.no_other_buffers").to_string());
+            return;
+        }
+        others.sort_by_key(|(id, _)| id.0);
+
+        let suggestions = others
+            .into_iter()
+            .map(|(id, name)| {
+                let modified = self
+                    .buffers
+                    .get(&id)
+                    .is_some_and(|state| state.buffer.is_modified());
+                Suggestion {
+                    description: modified.then(|| t!("compare.modified").to_string()),
+                    value: Some(id.0.to_string()),
+                    ..Suggestion::new(name)
+                }
+            })
+            .collect();
+        self.start_prompt_with_suggestions(
+            t!(
+                "compare.prompt",
+                name = self.get_buffer_display_name(active)
+            )
+            .to_string(),
+            PromptType::CompareBufferWith,
+            suggestions,
+        );
+    }
+
+    /// Compare the active buffer with the buffer picked in the prompt
+    pub(super) fn handle_compare_buffer_prompt(&mut self, input: &str) {
+        match input.trim().parse::<usize>() {
+            Ok(id) => self.compare_buffers(self.active_buffer(), BufferId(id)),
+            Err(_) => self.set_status_message(t!("compare.buffer_not_found").to_string()),
+        }
+    }
+
+    /// Show `left` and `right` side by side with their differences
+    /// highlighted, replacing any comparison already shown
+    pub fn compare_buffers(&mut self, left: BufferId, right: BufferId) {
+        self.start_buffer_comparison(left, None, right, None);
+    }
+
+    /// Compare the selection, or the whole active buffer, with the text on
+    /// the clipboard, shown in a read-only scratch buffer
+    pub fn compare_with_clipboard(&mut self) {
+        let Some(text) = self.clipboard.paste() else {
+            let message = match self.clipboard.system_state() {
+                SystemClipboardState::Slow { .. } => t!("compare.clipboard_unreadable"),
+                SystemClipboardState::Available => t!("compare.clipboard_empty"),
+            };
+            self.notify(NotificationSeverity::Warning, message);
+            return;
+        };
+        // The scratch buffer of an earlier comparison goes away first
+        self.end_buffer_comparison();
+
+        let left = self.active_buffer();
+        let region = self
+            .active_state()
+            .cursors
+            .primary()
+            .selection_range()
+            .filter(|range| !range.is_empty());
+        let text = self.with_buffer_line_endings(text.replace("\r\n", "\n").replace('\r', "\n"));
+        let scratch = self.create_clipboard_scratch_buffer(left, &text);
+        self.start_buffer_comparison(left, region, scratch, Some(scratch));
+    }
+
+    /// Read-only, unnamed buffer holding `text`, highlighted like `like`
+    fn create_clipboard_scratch_buffer(&mut self, like: BufferId, text: &str) -> BufferId {
+        let buffer_id = BufferId(self.next_buffer_id);
+        self.next_buffer_id += 1;
+
+        let mut state = EditorState::new(
+            self.terminal_width,
+            self.terminal_height,
+            self.config.editor.large_file_threshold_bytes as usize,
+        );
+        state.buffer = Buffer::from_str(text, 0);
+        state.marker_list.adjust_for_insert(0, text.len());
+        state.editing_disabled = true;
+        state.set_language_from_name(&self.get_buffer_display_name(like), &self.grammar_registry);
+        state
+            .margins
+            .set_line_numbers(self.config.editor.line_numbers);
+        self.buffers.insert(buffer_id, state);
+        self.event_logs.insert(buffer_id, EventLog::new());
+        self.buffer_metadata.insert(
+            buffer_id,
+            BufferMetadata::new_unnamed(t!("compare.clipboard_name").to_string()),
+        );
+        buffer_id
+    }
+
+    /// Show `left`, or its `region`, and `right` side by side, replacing any
+    /// comparison already shown; `scratch` is closed with the comparison
+    fn start_buffer_comparison(
+        &mut self,
+        left: BufferId,
+        region: Option<Range<usize>>,
+        right: BufferId,
+        scratch: Option<BufferId>,
+    ) {
+        if left == right {
+            self.set_status_message(t!("compare.same_buffer").to_string());
+            return;
+        }
+        if !self.buffers.contains_key(&left) || !self.buffers.contains_key(&right) {
+            self.set_status_message(t!("compare.buffer_not_found").to_string());
+            return;
+        }
+        self.end_buffer_comparison();
+
+        if self.active_buffer() != left {
+            self.set_active_buffer(left);
+        }
+        self.save_current_split_view_state();
+        let left_split = self.split_manager.active_split();
+        let right_split =
+            match self
+                .split_manager
+                .split_active(SplitDirection::Vertical, right, 0.5)
+            {
+                Ok(split) => split,
+                Err(e) => {
+                    if let Some(scratch) = scratch {
+                        let _ = self.force_close_buffer(scratch);
+                    }
+                    self.set_status_message(t!("split.error", error = e).to_string());
+                    return;
+                }
+            };
+        let mut view_state =
+            SplitViewState::with_buffer(self.terminal_width, self.terminal_height, right);
+        view_state.viewport.line_wrap_enabled = self.config.editor.line_wrap;
+        self.split_view_states.insert(right_split, view_state);
+        self.restore_current_split_view_state();
+
+        // Keep editing the left buffer
+        self.save_current_split_view_state();
+        self.split_manager.set_active_split(left_split);
+        self.restore_current_split_view_state();
+
+        let scroll_group = self
+            .scroll_sync_manager
+            .create_group(left_split, right_split);
+        let now = self.time_source.now();
+        self.buffer_comparison = Some(BufferComparison {
+            left,
+            right,
+            left_split,
+            right_split,
+            scroll_group,
+            left_region: None,
+            scratch,
+            hunks: Vec::new(),
+            computed: (0, 0),
+            seen: (0, 0),
+            seen_at: now,
+        });
+        if let Some(region) = region {
+            self.set_compare_left_region(region);
+        }
+        if self.recompute_buffer_comparison() {
+            let hunks = self.buffer_comparison.as_ref().map_or(0, |c| c.hunks.len());
+            let message = if hunks == 0 {
+                t!("compare.identical").to_string()
+            } else {
+                t!("compare.started", count = hunks).to_string()
+            };
+            self.set_status_message(message);
+        }
+    }
+
+    /// Diff the two buffers again, then highlight the hunks and anchor the
+    /// scrolling at them
+    ///
+    /// Ends the comparison and returns false if a buffer's contents are not
+    /// all loaded.
+    fn recompute_buffer_comparison(&mut self) -> bool {
+        let Some(comparison) = &self.buffer_comparison else {
+            return false;
+        };
+        let (left, right) = (comparison.left, comparison.right);
+        let region = self.compare_left_region();
+        let contents = |id: BufferId| {
+            let state = self.buffers.get(&id)?;
+            Some((state.buffer.revision(), state.buffer.get_all_text()?))
+        };
+        let (Some((left_revision, mut old)), Some((right_revision, new))) =
+            (contents(left), contents(right))
+        else {
+            self.end_buffer_comparison();
+            self.set_status_message(t!("compare.not_loaded").to_string());
+            return false;
+        };
+        // Byte and line of the left buffer where the compared text starts
+        let (left_start, left_start_line) = match region {
+            Some(region) => {
+                let end = region.end.min(old.len());
+                let start = region.start.min(end);
+                old = old[start..end].to_vec();
+                let line = self
+                    .buffers
+                    .get(&left)
+                    .map_or(0, |state| state.buffer.get_line_number(start));
+                (start, line)
+            }
+            None => (0, 0),
+        };
+
+        let old_starts = line_starts(&old);
+        let new_starts = line_starts(&new);
+        let matches = matching_lines(&old, &new);
+        let hunks = diff_hunks(&old, &old_starts, &new_starts, &matches);
+
+        let namespace = OverlayNamespace::from_string(COMPARE_NAMESPACE.to_string());
+        for (buffer_id, side, starts, len, base) in [
+            (left, Side::Left, &old_starts, old.len(), left_start),
+            (right, Side::Right, &new_starts, new.len(), 0),
+        ] {
+            let Some(state) = self.buffers.get_mut(&buffer_id) else {
+                continue;
+            };
+            state
+                .overlays
+                .clear_namespace(&namespace, &mut state.marker_list);
+            for hunk in &hunks {
+                let lines = hunk_lines(hunk, side);
+                if lines.is_empty() {
+                    continue;
+                }
+                let other_side_empty = match side {
+                    Side::Left => hunk.new_lines.is_empty(),
+                    Side::Right => hunk.old_lines.is_empty(),
+                };
+                let color = match (side, other_side_empty) {
+                    (_, false) => "diff.modified_bg",
+                    (Side::Left, true) => "diff.removed_bg",
+                    (Side::Right, true) => "diff.added_bg",
+                };
+                let face = OverlayFace::Themed {
+                    style: ThemedStyle {
+                        fg: None,
+                        bg: Some(DecorationColor::Named(color.to_string())),
+                        add_modifier: Default::default(),
+                    },
+                };
+                let range = base + starts[lines.start]..base + starts[lines.end].min(len);
+                let overlay =
+                    Overlay::with_namespace(&mut state.marker_list, range, face, namespace.clone())
+                        .with_extend_to_line_end(true);
+                state.overlays.add(overlay, &state.marker_list);
+            }
+        }
+
+        // Hunks on the left are kept in buffer lines and bytes
+        let hunks: Vec<Hunk> = hunks
+            .into_iter()
+            .map(|hunk| Hunk {
+                old_lines: hunk.old_lines.start + left_start_line
+                    ..hunk.old_lines.end + left_start_line,
+                old_bytes: hunk.old_bytes.start + left_start..hunk.old_bytes.end + left_start,
+                ..hunk
+            })
+            .collect();
+
+        let Some(comparison) = self.buffer_comparison.as_mut() else {
+            return false;
+        };
+        self.scroll_sync_manager.set_anchors(
+            comparison.scroll_group,
+            sync_anchors(&hunks, left_start_line),
+        );
+        comparison.hunks = hunks;
+        comparison.computed = (left_revision, right_revision);
+        comparison.seen = comparison.computed;
+        true
+    }
+
+    /// The part of the left buffer being compared, if not all of it
+    fn compare_left_region(&self) -> Option<Range<usize>> {
+        let comparison = self.buffer_comparison.as_ref()?;
+        let (start, end) = comparison.left_region?;
+        let markers = &self.buffers.get(&comparison.left)?.marker_list;
+        let start = markers.get_position(start)?;
+        let end = markers.get_position(end)?;
+        Some(start..end.max(start))
+    }
+
+    /// Compare `region` of the left buffer from now on
+    fn set_compare_left_region(&mut self, region: Range<usize>) {
+        let Some(comparison) = self.buffer_comparison.as_mut() else {
+            return;
+        };
+        let Some(state) = self.buffers.get_mut(&comparison.left) else {
+            return;
+        };
+        if let Some((start, end)) = comparison.left_region.take() {
+            state.marker_list.delete(start);
+            state.marker_list.delete(end);
+        }
+        comparison.left_region = Some((
+            state.marker_list.create(region.start, true),
+            state.marker_list.create(region.end, false),
+        ));
+    }
+
+    /// Remove the highlighting and scroll sync of the comparison, if any,
+    /// and close its scratch buffer unless it was saved
+    fn end_buffer_comparison(&mut self) {
+        let Some(comparison) = self.buffer_comparison.take() else {
+            return;
+        };
+        self.scroll_sync_manager
+            .remove_group(comparison.scroll_group);
+        let namespace = OverlayNamespace::from_string(COMPARE_NAMESPACE.to_string());
+        for buffer_id in [comparison.left, comparison.right] {
+            if let Some(state) = self.buffers.get_mut(&buffer_id) {
+                state
+                    .overlays
+                    .clear_namespace(&namespace, &mut state.marker_list);
+            }
+        }
+        if let (Some((start, end)), Some(state)) = (
+            comparison.left_region,
+            self.buffers.get_mut(&comparison.left),
+        ) {
+            state.marker_list.delete(start);
+            state.marker_list.delete(end);
+        }
+        if let Some(scratch) = comparison.scratch {
+            let saved = self
+                .buffers
+                .get(&scratch)
+                .map(|state| state.buffer.file_path().is_some());
+            match saved {
+                // Saved as a file, it stays open as an ordinary buffer
+                Some(true) => {
+                    if let Some(state) = self.buffers.get_mut(&scratch) {
+                        state.editing_disabled = false;
+                    }
+                }
+                Some(false) => {
+                    let _ = self.force_close_buffer(scratch);
+                }
+                None => {}
+            }
+        }
+    }
+
+    /// End the comparison when one of its buffers is closed
+    pub(super) fn on_compare_buffer_closed(&mut self, buffer_id: BufferId) {
+        let Some(comparison) = self.buffer_comparison.as_mut() else {
+            return;
+        };
+        if comparison.left == buffer_id || comparison.right == buffer_id {
+            // The scratch buffer is already being closed
+            if comparison.scratch == Some(buffer_id) {
+                comparison.scratch = None;
+            }
+            self.end_buffer_comparison();
+        }
+    }
+
+    /// Recompute the hunks once the buffers have gone unedited for a moment,
+    /// and end the comparison when one of its splits was closed
+    ///
+    /// Returns true if anything visible changed.
+    pub(super) fn poll_buffer_comparison(&mut self) -> bool {
+        let Some(comparison) = &self.buffer_comparison else {
+            return false;
+        };
+        if !self.split_view_states.contains_key(&comparison.left_split)
+            || !self.split_view_states.contains_key(&comparison.right_split)
+        {
+            self.end_buffer_comparison();
+            return true;
+        }
+        let revision = |id: BufferId| self.buffers.get(&id).map(|s| s.buffer.revision());
+        let (Some(left), Some(right)) = (revision(comparison.left), revision(comparison.right))
+        else {
+            return false;
+        };
+        if (left, right) == comparison.computed {
+            return false;
+        }
+
+        let now = self.time_source.now();
+        let Some(comparison) = self.buffer_comparison.as_mut() else {
+            return false;
+        };
+        if (left, right) != comparison.seen {
+            comparison.seen = (left, right);
+            comparison.seen_at = now;
+            return false;
+        }
+        if now.duration_since(comparison.seen_at) < RECOMPUTE_DELAY {
+            return false;
+        }
+        self.recompute_buffer_comparison()
+    }
+
+    /// The side the active buffer is on and the line of its cursor, with
+    /// the comparison's hunks recomputed if the buffers changed
+    fn compare_cursor(&mut self) -> Option<(Side, usize)> {
+        let comparison = self.buffer_comparison.as_ref()?;
+        let active = self.active_buffer();
+        let side = if active == comparison.left {
+            Side::Left
+        } else if active == comparison.right {
+            Side::Right
+        } else {
+            return None;
+        };
+        let (left, right, computed) = (comparison.left, comparison.right, comparison.computed);
+        let revision = |id: BufferId| self.buffers.get(&id).map(|s| s.buffer.revision());
+        if (revision(left)?, revision(right)?) != computed && !self.recompute_buffer_comparison() {
+            return None;
+        }
+        let state = self.active_state();
+        let line = state
+            .buffer
+            .get_line_number(state.cursors.primary().position);
+        Some((side, line))
+    }
+
+    /// Move the cursor to the next (or previous) hunk of the comparison
+    pub fn compare_goto_hunk(&mut self, forward: bool) {
+        let Some((side, line)) = self.compare_cursor() else {
+            self.set_status_message(t!("compare.not_comparing").to_string());
+            return;
+        };
+        let target = self
+            .buffer_comparison
+            .as_ref()
+            .and_then(|c| next_hunk_line(&c.hunks, side, line, forward));
+        match target {
+            Some(target) => self.goto_line_col(target + 1, None),
+            None => self.set_status_message(t!("compare.no_more_hunks").to_string()),
+        }
+    }
+
+    /// Replace the hunk at the cursor on the left (or right) side with the
+    /// other side's lines, as one undoable edit
+    pub fn compare_copy_hunk(&mut self, to_left: bool) {
+        let Some((side, line)) = self.compare_cursor() else {
+            self.set_status_message(t!("compare.not_comparing").to_string());
+            return;
+        };
+        let Some(comparison) = &self.buffer_comparison else {
+            return;
+        };
+        let Some(hunk) = hunk_at_line(&comparison.hunks, side, line)
+            .map(|index| comparison.hunks[index].clone())
+        else {
+            self.set_status_message(t!("compare.no_hunk_at_cursor").to_string());
+            return;
+        };
+        let (target, source, target_bytes, source_bytes) = if to_left {
+            (
+                comparison.left,
+                comparison.right,
+                hunk.old_bytes,
+                hunk.new_bytes,
+            )
+        } else {
+            (
+                comparison.right,
+                comparison.left,
+                hunk.new_bytes,
+                hunk.old_bytes,
+            )
+        };
+
+        let Some(text) = self.buffers.get_mut(&source).and_then(|state| {
+            state
+                .buffer
+                .get_text_range_mut(source_bytes.start, source_bytes.len())
+                .ok()
+        }) else {
+            return;
+        };
+        let text = String::from_utf8_lossy(&text).into_owned();
+        let text_len = text.len();
+        let left_region = if to_left {
+            self.compare_left_region()
+        } else {
+            None
+        };
+        let Some(state) = self.buffers.get_mut(&target) else {
+            return;
+        };
+        if state.editing_disabled {
+            self.set_status_message(t!("compare.read_only").to_string());
+            return;
+        }
+        let Ok(deleted) = state
+            .buffer
+            .get_text_range_mut(target_bytes.start, target_bytes.len())
+        else {
+            return;
+        };
+        let cursor_id = state.cursors.primary_id();
+        let mut events = Vec::with_capacity(2);
+        if !target_bytes.is_empty() {
+            events.push(Event::Delete {
+                range: target_bytes.clone(),
+                deleted_text: String::from_utf8_lossy(&deleted).into_owned(),
+                cursor_id,
+            });
+            state
+                .marker_list
+                .adjust_for_delete(target_bytes.start, target_bytes.len());
+            state
+                .margins
+                .adjust_for_delete(target_bytes.start, target_bytes.len());
+        }
+        if !text.is_empty() {
+            state
+                .marker_list
+                .adjust_for_insert(target_bytes.start, text.len());
+            state
+                .margins
+                .adjust_for_insert(target_bytes.start, text.len());
+            events.push(Event::Insert {
+                position: target_bytes.start,
+                text,
+                cursor_id,
+            });
+        }
+
+        let description = t!("compare.copy_hunk").to_string();
+        if let Err(e) = self.apply_events_to_buffer_as_bulk_edit(
+            target,
+            events,
+            description.clone(),
+            Some(description),
+        ) {
+            tracing::error!("Failed to copy hunk: {}", e);
+            return;
+        }
+        // The hunk is inside the compared region, which grows or shrinks
+        // with it
+        if let Some(region) = left_region {
+            let end = (region.end + text_len).saturating_sub(target_bytes.len());
+            self.set_compare_left_region(region.start..end.max(region.start));
+        }
+        self.recompute_buffer_comparison();
+        let remaining = self.buffer_comparison.as_ref().map_or(0, |c| c.hunks.len());
+        self.set_status_message(t!("compare.hunk_copied", count = remaining).to_string());
+    }
+
+    /// Line ranges of the comparison's hunks on the left and right (for
+    /// testing)
+    pub fn compare_hunks(&self) -> Option<Vec<(Range<usize>, Range<usize>)>> {
+        self.buffer_comparison.as_ref().map(|comparison| {
+            comparison
+                .hunks
+                .iter()
+                .map(|hunk| (hunk.old_lines.clone(), hunk.new_lines.clone()))
+                .collect()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hunks(old: &str, new: &str) -> Vec<Hunk> {
+        let (old, new) = (old.as_bytes(), new.as_bytes());
+        let matches = matching_lines(old, new);
+        diff_hunks(old, &line_starts(old), &line_starts(new), &matches)
+    }
+
+    #[test]
+    fn test_hunk_at_line() {
+        // "b" changed, "d" removed on the right
+        let hunks = hunks("a\nb\nc\nd\ne\n", "a\nB\nc\ne\n");
+        assert_eq!(hunk_at_line(&hunks, Side::Left, 0), None);
+        assert_eq!(hunk_at_line(&hunks, Side::Left, 1), Some(0));
+        assert_eq!(hunk_at_line(&hunks, Side::Left, 3), Some(1));
+        assert_eq!(hunk_at_line(&hunks, Side::Right, 1), Some(0));
+        // The removed line would go before "e" on the right
+        assert_eq!(hunk_at_line(&hunks, Side::Right, 3), Some(1));
+        assert_eq!(hunk_at_line(&hunks, Side::Right, 2), None);
+    }
+
+    #[test]
+    fn test_next_hunk_line() {
+        let hunks = hunks("a\nb\nc\nd\ne\n", "a\nB\nc\ne\n");
+        assert_eq!(next_hunk_line(&hunks, Side::Left, 0, true), Some(1));
+        assert_eq!(next_hunk_line(&hunks, Side::Left, 1, true), Some(3));
+        assert_eq!(next_hunk_line(&hunks, Side::Left, 3, true), None);
+        assert_eq!(next_hunk_line(&hunks, Side::Left, 4, false), Some(3));
+        assert_eq!(next_hunk_line(&hunks, Side::Right, 3, false), Some(1));
+        assert_eq!(next_hunk_line(&hunks, Side::Right, 1, false), None);
+    }
+
+    #[test]
+    fn test_sync_anchors_follow_hunks() {
+        // Two lines added on the right after "a"
+        let hunks = hunks("a\nb\n", "a\nx\ny\nb\n");
+        let anchors: Vec<_> = sync_anchors(&hunks, 0)
+            .iter()
+            .map(|a| (a.left_line, a.right_line))
+            .collect();
+        assert_eq!(anchors, vec![(0, 0), (1, 1), (1, 3)]);
+    }
+}
@@ -10,11 +10,17 @@
 
 use rust_i18n::t;
 use std::io;
+use std::ops::Range;
 use std::path::Path;
+use std::sync::Arc;
 
+use crate::app::buffer_pins::with_kept_pinned;
 use crate::app::warning_domains::WarningDomain;
 use crate::model::event::{BufferId, Event, SplitId};
+use crate::primitives::indent_rules::IndentRules;
+use crate::services::file_provider::is_remote_path;
 use crate::services::lsp::manager::detect_language;
+use crate::services::open_target::{self, OpenTarget};
 use crate::state::EditorState;
 use crate::view::prompt::PromptType;
 use crate::view::split::SplitViewState;
@@ -64,8 +70,10 @@ impl Editor {
             .map(|s| s.buffer.is_binary())
             .unwrap_or(false);
 
-        // Show appropriate status message for binary vs regular files
-        if is_binary {
+        // Show appropriate status message for loading, binary and regular files
+        if self.is_buffer_loading(buffer_id) {
+            self.status_message = Some(t!("buffer.loading", name = display_name).to_string());
+        } else if is_binary {
             self.status_message = Some(t!("buffer.opened_binary", name = display_name).to_string());
         } else {
             self.status_message = Some(t!("buffer.opened", name = display_name).to_string());
@@ -80,7 +88,14 @@ impl Editor {
     /// but does not change the active buffer. Useful for opening files in background tabs.
     ///
     /// If the file doesn't exist, creates an unsaved buffer with that filename.
+    /// Directories and special files such as FIFOs are refused with an error;
+    /// [`Editor::open_path`] shows directories in the file explorer instead.
     pub fn open_file_no_focus(&mut self, path: &Path) -> io::Result<BufferId> {
+        // URLs such as ssh://host/path name files elsewhere
+        if is_remote_path(path) {
+            return self.open_remote_no_focus(path);
+        }
+
         // Resolve relative paths against working_dir, not process current directory
         let resolved_path = if path.is_relative() {
             self.working_dir.join(path)
@@ -89,12 +104,46 @@ impl Editor {
         };
 
         // Determine if we're opening a non-existent file (for creating new files)
-        let file_exists = resolved_path.exists();
+        let file_exists = match open_target::classify(&resolved_path)? {
+            OpenTarget::File => true,
+            OpenTarget::Missing => false,
+            OpenTarget::Directory => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    t!(
+                        "file.is_directory",
+                        path = resolved_path.display().to_string()
+                    )
+                    .to_string(),
+                ))
+            }
+            OpenTarget::Special(kind) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    t!(
+                        "file.special_file",
+                        path = resolved_path.display().to_string(),
+                        kind = kind.as_str()
+                    )
+                    .to_string(),
+                ))
+            }
+        };
+
+        let canonical_path = self.canonical_file_path(resolved_path, file_exists);
+        self.open_file_at(&canonical_path, file_exists)
+    }
 
+    /// The path a buffer for the file at `resolved_path` is opened under
+    pub(super) fn canonical_file_path(
+        &self,
+        resolved_path: std::path::PathBuf,
+        file_exists: bool,
+    ) -> std::path::PathBuf {
         // Canonicalize the path to resolve symlinks and normalize path components
         // This ensures consistent path representation throughout the editor
         // For non-existent files, we need to canonicalize the parent directory and append the filename
-        let canonical_path = if file_exists {
+        if file_exists {
             resolved_path
                 .canonicalize()
                 .unwrap_or_else(|_| resolved_path.clone())
@@ -117,9 +166,13 @@ impl Editor {
             } else {
                 resolved_path
             }
-        };
-        let path = canonical_path.as_path();
+        }
+    }
 
+    /// Open the file at a canonical path without switching focus to it
+    ///
+    /// `file_exists` is false for files that saving the buffer will create.
+    pub(super) fn open_file_at(&mut self, path: &Path, file_exists: bool) -> io::Result<BufferId> {
         // Check if file is already open - return existing buffer without switching
         let already_open = self
             .buffers
@@ -149,16 +202,11 @@ impl Editor {
             id
         };
 
-        // Create the editor state - either load from file or create empty buffer
-        let mut state = if file_exists {
-            EditorState::from_file_with_languages(
-                path,
-                self.terminal_width,
-                self.terminal_height,
-                self.config.editor.large_file_threshold_bytes as usize,
-                &self.grammar_registry,
-                &self.config.languages,
-            )?
+        // Create the editor state - either load from file or create empty buffer.
+        // Files whose contents don't arrive within the quick-load window get a
+        // placeholder buffer that is filled in when the background read ends.
+        let (mut state, pending_load) = if file_exists {
+            self.load_file_state(path)?
         } else {
             // File doesn't exist - create empty buffer with the file path set
             let mut new_state = EditorState::new(
@@ -168,26 +216,59 @@ impl Editor {
             );
             // Set the file path so saving will create the file
             new_state.buffer.set_file_path(path.to_path_buf());
-            new_state
+            (new_state, None)
         };
         // Note: line_wrap_enabled is set on SplitViewState.viewport when the split is created
 
+        self.configure_file_state(&mut state, path);
+
+        self.buffers.insert(buffer_id, state);
+        self.event_logs
+            .insert(buffer_id, crate::model::event::EventLog::new());
+
+        // Create metadata for this buffer
+        let metadata =
+            super::types::BufferMetadata::with_file(path.to_path_buf(), &self.working_dir);
+        self.buffer_metadata.insert(buffer_id, metadata);
+
+        // Add buffer to the active split's tabs (but don't switch to it)
+        let active_split = self.split_manager.active_split();
+        if let Some(view_state) = self.split_view_states.get_mut(&active_split) {
+            view_state.add_buffer(buffer_id);
+            // Apply line_wrap default from config (per-view setting, applies to split)
+            view_state.viewport.line_wrap_enabled = self.config.editor.line_wrap;
+        }
+
+        // Track file for auto-revert and conflict detection
+        self.watch_file(path);
+
+        match pending_load {
+            Some(load) => self.track_file_load(buffer_id, load, active_split),
+            None => self.finish_file_open(buffer_id, path, active_split),
+        }
+
+        Ok(buffer_id)
+    }
+
+    /// Apply language and editor settings to the state of a newly opened file
+    pub(super) fn configure_file_state(&self, state: &mut EditorState, path: &Path) {
         // Check if the buffer contains binary content
-        let is_binary = state.buffer.is_binary();
-        if is_binary {
+        if state.buffer.is_binary() {
             // Make binary buffers read-only
             state.editing_disabled = true;
             tracing::info!("Detected binary file: {}", path.display());
         }
 
-        // Set show_whitespace_tabs, use_tabs, and tab_size based on language config
-        // with fallback to global editor config for tab_size
+        // Set show_whitespace_tabs, use_tabs, tab_size and rulers based on language
+        // config with fallback to global editor config for tab_size
         if let Some(language) = detect_language(path, &self.config.languages) {
             if let Some(lang_config) = self.config.languages.get(&language) {
                 state.show_whitespace_tabs = lang_config.show_whitespace_tabs;
                 state.use_tabs = lang_config.use_tabs;
                 // Use language-specific tab_size if set, otherwise fall back to global
                 state.tab_size = lang_config.tab_size.unwrap_or(self.config.editor.tab_size);
+                state.rulers = lang_config.rulers.clone();
+                state.indent_rules = self.indent_rules_for_language(&language);
             } else {
                 state.tab_size = self.config.editor.tab_size;
             }
@@ -199,41 +280,40 @@ impl Editor {
         state
             .margins
             .set_line_numbers(self.config.editor.line_numbers);
+    }
 
-        self.buffers.insert(buffer_id, state);
-        self.event_logs
-            .insert(buffer_id, crate::model::event::EventLog::new());
-
-        // Create metadata for this buffer
-        let mut metadata =
-            super::types::BufferMetadata::with_file(path.to_path_buf(), &self.working_dir);
-
-        // Mark binary files in metadata and disable LSP
-        if is_binary {
-            metadata.binary = true;
-            metadata.read_only = true;
-            metadata.disable_lsp(t!("buffer.binary_file").to_string());
-        }
-
-        // Notify LSP about the newly opened file (skip for binary files)
-        if !is_binary {
-            self.notify_lsp_file_opened(path, buffer_id, &mut metadata);
-        }
-
-        // Store metadata for this buffer
-        self.buffer_metadata.insert(buffer_id, metadata);
-
-        // Add buffer to the active split's tabs (but don't switch to it)
-        let active_split = self.split_manager.active_split();
-        if let Some(view_state) = self.split_view_states.get_mut(&active_split) {
-            view_state.add_buffer(buffer_id);
-            // Apply line_wrap default from config (per-view setting, applies to split)
-            view_state.viewport.line_wrap_enabled = self.config.editor.line_wrap;
+    /// Finish opening a file once its contents are in the buffer
+    ///
+    /// Notifies LSP and plugins and restores the saved cursor/scroll position.
+    /// For files loaded in the background this runs when the load completes.
+    pub(super) fn finish_file_open(&mut self, buffer_id: BufferId, path: &Path, split_id: SplitId) {
+        let is_binary = self
+            .buffers
+            .get(&buffer_id)
+            .is_some_and(|state| state.buffer.is_binary());
+
+        if let Some(mut metadata) = self.buffer_metadata.remove(&buffer_id) {
+            if is_binary {
+                // Mark binary files in metadata and disable LSP
+                metadata.binary = true;
+                metadata.read_only = true;
+                metadata.disable_lsp(t!("buffer.binary_file").to_string());
+            } else if is_remote_path(path) {
+                // Language servers only see the local file system
+                metadata.disable_lsp(t!("lsp.disabled.remote").to_string());
+            } else {
+                // Notify LSP about the newly opened file
+                self.notify_lsp_file_opened(path, buffer_id, &mut metadata);
+            }
+            self.buffer_metadata.insert(buffer_id, metadata);
         }
+        self.refresh_file_writability(buffer_id);
+        self.refresh_file_protection(buffer_id);
+        self.record_usage_file_open(path);
 
         // Restore global file state (scroll/cursor position) if available
         // This persists file positions across projects and editor instances
-        self.restore_global_file_state(buffer_id, path, active_split);
+        self.restore_global_file_state(buffer_id, path, split_id);
 
         // Emit control event
         self.emit_event(
@@ -244,9 +324,6 @@ impl Editor {
             }),
         );
 
-        // Track file for auto-revert and conflict detection
-        self.watch_file(path);
-
         // Fire AfterFileOpen hook for plugins
         self.plugin_manager.run_hook(
             "after_file_open",
@@ -255,8 +332,18 @@ impl Editor {
                 path: path.to_path_buf(),
             },
         );
+    }
 
-        Ok(buffer_id)
+    /// Compile the auto-indent rules configured for a language, if any
+    pub(super) fn indent_rules_for_language(&self, language: &str) -> Option<Arc<IndentRules>> {
+        let rules = self.config.languages.get(language)?.indent_rules.as_ref()?;
+        match IndentRules::compile(rules) {
+            Ok(rules) => Some(Arc::new(rules)),
+            Err(e) => {
+                tracing::warn!("Invalid indent rules for {}: {}", language, e);
+                None
+            }
+        }
     }
 
     /// Restore global file state (cursor and scroll position) for a newly opened file
@@ -267,7 +354,8 @@ impl Editor {
         use crate::session::PersistedFileSession;
 
         // Load the per-file session for this path (lazy load from disk)
-        let file_state = match PersistedFileSession::load(path) {
+        let file_state = match PersistedFileSession::load(&self.dir_context.file_states_dir(), path)
+        {
             Some(state) => state,
             None => return, // No saved state for this file
         };
@@ -345,7 +433,7 @@ impl Editor {
         };
 
         // Save to disk
-        PersistedFileSession::save(&abs_path, file_state);
+        PersistedFileSession::save(&self.dir_context.file_states_dir(), &abs_path, file_state);
         tracing::debug!("Saved file state on close for {:?}", abs_path);
     }
 
@@ -359,6 +447,11 @@ impl Editor {
             return; // Line numbers are 1-indexed
         }
 
+        // The position is applied once a loading buffer has its contents
+        if self.defer_goto_line(line, column) {
+            return;
+        }
+
         let buffer_id = self.active_buffer();
         let estimated_line_length = self.config.editor.estimated_line_length;
 
@@ -459,6 +552,55 @@ impl Editor {
         buffer_id
     }
 
+    /// Create an untitled buffer holding `content`, as a plugin's template
+    ///
+    /// The buffer is like one made with [`Self::new_buffer`]: saving asks for
+    /// a path, and it starts modified unless `content` is empty. `language`,
+    /// a key of the `languages` config, picks the highlighting; without it
+    /// the extension of `name` does. `name` is shown in the tab and buffer
+    /// switcher until the buffer is saved.
+    pub fn create_untitled_buffer(
+        &mut self,
+        content: &str,
+        language: Option<&str>,
+        name: Option<String>,
+    ) -> Result<BufferId, String> {
+        let highlight_name = match language {
+            Some(language) => Some(self.language_file_name(language)?),
+            None => name.clone(),
+        };
+
+        let buffer_id = self.new_buffer();
+        if let Some(state) = self.buffers.get_mut(&buffer_id) {
+            if let Some(highlight_name) = &highlight_name {
+                state.set_language_from_name(highlight_name, &self.grammar_registry);
+            }
+            if !content.is_empty() {
+                state.buffer.insert(0, content);
+            }
+        }
+        if let Some(name) = name {
+            let metadata = super::types::BufferMetadata::new_unnamed(name);
+            self.buffer_metadata.insert(buffer_id, metadata);
+        }
+        Ok(buffer_id)
+    }
+
+    /// A file name the `languages` config gives `language`, for picking
+    /// highlighting by name
+    fn language_file_name(&self, language: &str) -> Result<String, String> {
+        let config = self
+            .config
+            .languages
+            .get(language)
+            .ok_or_else(|| format!("Unknown language: {}", language))?;
+        config
+            .extensions
+            .first()
+            .map(|extension| format!("untitled.{}", extension))
+            .ok_or_else(|| format!("Language {} has no file extensions", language))
+    }
+
     /// Create a new buffer from stdin content stored in a temp file
     ///
     /// Uses lazy chunk loading for efficient handling of large stdin inputs.
@@ -730,6 +872,12 @@ impl Editor {
         buffer_id: BufferId,
         entries: Vec<crate::primitives::text_property::TextPropertyEntry>,
     ) -> Result<(), String> {
+        if !self.buffers.contains_key(&buffer_id) {
+            return Err("Buffer not found".to_string());
+        }
+        // Content still waiting to be taken in is replaced too
+        self.pending_virtual_content.remove(&buffer_id);
+        let (entries, ansi_spans) = self.strip_ansi_entries(buffer_id, entries);
         let state = self
             .buffers
             .get_mut(&buffer_id)
@@ -752,9 +900,21 @@ impl Editor {
         // Clear modified flag since this is virtual buffer content setting, not user edits
         state.buffer.clear_modified();
 
+        // A restored panel no longer needs its placeholder once the plugin fills it
+        if let Some(panel) = self.persistent_panels.get_mut(&buffer_id) {
+            panel.restore_deadline = None;
+        }
+
         // Set text properties
         state.text_properties = properties;
 
+        // Decorations point into the replaced text; their owner adds them
+        // again for the new content
+        state.overlays.clear(&mut state.marker_list);
+        state.virtual_texts.clear(&mut state.marker_list);
+        state.margins.clear_line_indicators();
+        state.margins.clear_all();
+
         // Preserve cursor position (clamped to new content length and snapped to char boundary)
         let new_len = state.buffer.len();
         let clamped_pos = old_cursor_pos.min(new_len);
@@ -763,6 +923,76 @@ impl Editor {
         state.cursors.primary_mut().position = new_cursor_pos;
         state.cursors.primary_mut().anchor = None;
 
+        if let Some(spans) = ansi_spans {
+            self.apply_ansi_spans(buffer_id, &spans);
+        }
+
+        Ok(())
+    }
+
+    /// Replace `range` of a virtual buffer's content with `entries`, keeping
+    /// the text and text properties around it
+    ///
+    /// Lets a panel redraw the part of its content that changed. Cursors
+    /// after the range move with the text; a cursor inside it stays at the
+    /// same offset into the new text.
+    pub fn splice_virtual_buffer_content(
+        &mut self,
+        buffer_id: BufferId,
+        range: Range<usize>,
+        entries: Vec<crate::primitives::text_property::TextPropertyEntry>,
+    ) -> Result<(), String> {
+        // The range refers to all of the content sent
+        self.flush_virtual_content(buffer_id);
+        self.splice_virtual_content_now(buffer_id, range, entries)
+    }
+
+    /// [`Self::splice_virtual_buffer_content`], leaving content still waiting
+    /// to be taken in where it is
+    pub(super) fn splice_virtual_content_now(
+        &mut self,
+        buffer_id: BufferId,
+        range: Range<usize>,
+        entries: Vec<crate::primitives::text_property::TextPropertyEntry>,
+    ) -> Result<(), String> {
+        let state = self
+            .buffers
+            .get_mut(&buffer_id)
+            .ok_or_else(|| "Buffer not found".to_string())?;
+        if range.start > range.end || range.end > state.buffer.len() {
+            return Err("Range out of bounds".to_string());
+        }
+        let (text, properties) =
+            crate::primitives::text_property::TextPropertyManager::from_entries(entries);
+
+        if !range.is_empty() {
+            state.buffer.delete_bytes(range.start, range.len());
+        }
+        if !text.is_empty() {
+            state.buffer.insert(range.start, &text);
+        }
+        state.buffer.clear_modified();
+        state
+            .text_properties
+            .splice(range.clone(), text.len(), properties);
+
+        let new_end = range.start + text.len();
+        let moved = |position: usize| {
+            if position >= range.end {
+                position - range.len() + text.len()
+            } else if position > range.start {
+                position.min(new_end)
+            } else {
+                position
+            }
+        };
+        let buffer = &state.buffer;
+        state.cursors.map(|cursor| {
+            cursor.position = buffer.snap_to_char_boundary(moved(cursor.position));
+            cursor.anchor = cursor
+                .anchor
+                .map(|anchor| buffer.snap_to_char_boundary(moved(anchor)));
+        });
         Ok(())
     }
 
@@ -803,77 +1033,6 @@ impl Editor {
         self.set_active_buffer(buffer_id);
     }
 
-    /// Open the keyboard shortcuts viewer in a read-only buffer
-    ///
-    /// If a keyboard shortcuts buffer already exists, switch to it instead of creating a new one.
-    /// The shortcuts are dynamically generated from the current keybindings configuration.
-    pub fn open_keyboard_shortcuts(&mut self) {
-        // Check if keyboard shortcuts buffer already exists
-        let existing_buffer = self
-            .buffer_metadata
-            .iter()
-            .find(|(_, m)| m.display_name == help::KEYBOARD_SHORTCUTS_BUFFER_NAME)
-            .map(|(id, _)| *id);
-
-        if let Some(buffer_id) = existing_buffer {
-            // Switch to existing buffer
-            self.set_active_buffer(buffer_id);
-            return;
-        }
-
-        // Get all keybindings
-        let bindings = self.keybindings.get_all_bindings();
-
-        // Format the keybindings as readable text
-        let mut content = String::from("Keyboard Shortcuts\n");
-        content.push_str("==================\n\n");
-        content.push_str("Press 'q' to close this buffer.\n\n");
-
-        // Group bindings by context (Normal, Prompt, etc.)
-        let mut current_context = String::new();
-        for (key, action) in &bindings {
-            // Check if action starts with a context prefix like "[Prompt] "
-            let (context, action_name) = if let Some(bracket_end) = action.find("] ") {
-                let ctx = &action[1..bracket_end];
-                let name = &action[bracket_end + 2..];
-                (ctx.to_string(), name.to_string())
-            } else {
-                ("Normal".to_string(), action.clone())
-            };
-
-            // Print context header when it changes
-            if context != current_context {
-                if !current_context.is_empty() {
-                    content.push('\n');
-                }
-                content.push_str(&format!("── {} Mode ──\n\n", context));
-                current_context = context;
-            }
-
-            // Format: "  Ctrl+S          Save"
-            content.push_str(&format!("  {:20} {}\n", key, action_name));
-        }
-
-        // Create new keyboard shortcuts buffer with "special" mode (has 'q' to close)
-        let buffer_id = self.create_virtual_buffer(
-            help::KEYBOARD_SHORTCUTS_BUFFER_NAME.to_string(),
-            "special".to_string(),
-            true,
-        );
-
-        // Set the content
-        if let Some(state) = self.buffers.get_mut(&buffer_id) {
-            state.buffer.insert(0, &content);
-            state.buffer.clear_modified();
-            state.editing_disabled = true;
-
-            // Disable line numbers for cleaner display
-            state.margins.set_line_numbers(false);
-        }
-
-        self.set_active_buffer(buffer_id);
-    }
-
     /// Show warnings by opening the warning log file directly
     ///
     /// If there are no warnings, shows a brief status message.
@@ -928,6 +1087,15 @@ impl Editor {
         if !self.warning_domains.lsp.has_warnings() {
             if self.lsp_status.is_empty() {
                 self.status_message = Some(t!("lsp.no_server_active").to_string());
+            } else if let Some(servers) = self.lsp_server_sync_summary() {
+                self.status_message = Some(
+                    t!(
+                        "lsp.status_with_sync",
+                        status = &self.lsp_status,
+                        servers = servers
+                    )
+                    .to_string(),
+                );
             } else {
                 self.status_message = Some(t!("lsp.status", status = &self.lsp_status).to_string());
             }
@@ -976,8 +1144,22 @@ impl Editor {
 
     /// Internal helper to close a buffer (shared by close_buffer and force_close_buffer)
     fn close_buffer_internal(&mut self, id: BufferId) -> io::Result<()> {
-        // Save file state before closing (for per-file session persistence)
-        self.save_file_state_on_close(id);
+        // Closing the pending edits review buffer cancels the review
+        self.on_pending_edits_buffer_closed(id);
+        self.on_todos_buffer_closed(id);
+        self.on_dir_compare_buffer_closed(id);
+        self.on_workspace_diagnostics_buffer_closed(id);
+        self.on_remote_dir_buffer_closed(id);
+        self.on_formatted_buffer_closed(id);
+        self.on_save_actions_buffer_closed(id);
+        self.on_inline_blame_buffer_closed(id);
+        self.on_compare_buffer_closed(id);
+
+        // Save file state before closing (for per-file session persistence),
+        // unless the buffer never received the file's contents
+        if !self.discard_file_load(id) {
+            self.save_file_state_on_close(id);
+        }
 
         // If closing a terminal buffer while in terminal mode, exit terminal mode
         if self.terminal_mode && self.is_terminal_buffer(id) {
@@ -1010,6 +1192,10 @@ impl Editor {
         // Remove buffer from panel_ids mapping if it was a panel buffer
         // This prevents stale entries when the same panel_id is reused later
         self.panel_ids.retain(|_, &mut buf_id| buf_id != id);
+        self.persistent_panels.remove(&id);
+        self.forget_ansi_colors(id);
+        self.pending_virtual_content.remove(&id);
+        self.on_plugin_buffer_closed(id);
 
         // Remove buffer from all splits' open_buffers lists
         for view_state in self.split_view_states.values_mut() {
@@ -1223,11 +1409,13 @@ impl Editor {
             .unwrap_or_default();
 
         // Close all tabs except the one we want to keep
-        let tabs_to_close: Vec<_> = split_tabs
-            .iter()
-            .filter(|&&id| id != keep_buffer_id)
-            .copied()
-            .collect();
+        let (tabs_to_close, skipped_pinned) = self.without_pinned(
+            split_tabs
+                .iter()
+                .filter(|&&id| id != keep_buffer_id)
+                .copied()
+                .collect(),
+        );
 
         let mut closed = 0;
         let mut skipped_modified = 0;
@@ -1244,7 +1432,7 @@ impl Editor {
             .split_manager
             .set_split_buffer(split_id, keep_buffer_id);
 
-        self.set_batch_close_status_message(closed, skipped_modified);
+        self.set_batch_close_status_message(closed, skipped_modified, skipped_pinned);
     }
 
     /// Close tabs to the right of the specified buffer in a split
@@ -1262,7 +1450,8 @@ impl Editor {
         };
 
         // Close all tabs after the target
-        let tabs_to_close: Vec<_> = split_tabs.iter().skip(target_idx + 1).copied().collect();
+        let (tabs_to_close, skipped_pinned) =
+            self.without_pinned(split_tabs.iter().skip(target_idx + 1).copied().collect());
 
         let mut closed = 0;
         let mut skipped_modified = 0;
@@ -1274,7 +1463,7 @@ impl Editor {
             }
         }
 
-        self.set_batch_close_status_message(closed, skipped_modified);
+        self.set_batch_close_status_message(closed, skipped_modified, skipped_pinned);
     }
 
     /// Close tabs to the left of the specified buffer in a split
@@ -1292,7 +1481,8 @@ impl Editor {
         };
 
         // Close all tabs before the target
-        let tabs_to_close: Vec<_> = split_tabs.iter().take(target_idx).copied().collect();
+        let (tabs_to_close, skipped_pinned) =
+            self.without_pinned(split_tabs.iter().take(target_idx).copied().collect());
 
         let mut closed = 0;
         let mut skipped_modified = 0;
@@ -1304,7 +1494,7 @@ impl Editor {
             }
         }
 
-        self.set_batch_close_status_message(closed, skipped_modified);
+        self.set_batch_close_status_message(closed, skipped_modified, skipped_pinned);
     }
 
     /// Close all tabs in a split
@@ -1316,11 +1506,12 @@ impl Editor {
             .map(|vs| vs.open_buffers.clone())
             .unwrap_or_default();
 
+        let (tabs_to_close, skipped_pinned) = self.without_pinned(split_tabs);
         let mut closed = 0;
         let mut skipped_modified = 0;
 
         // Close all tabs (this will eventually close the split when empty)
-        for buffer_id in split_tabs {
+        for buffer_id in tabs_to_close {
             if self.close_tab_in_split_silent(buffer_id, split_id) {
                 closed += 1;
             } else {
@@ -1328,18 +1519,65 @@ impl Editor {
             }
         }
 
-        self.set_batch_close_status_message(closed, skipped_modified);
+        self.set_batch_close_status_message(closed, skipped_modified, skipped_pinned);
+    }
+
+    /// Close every file buffer without unsaved changes, except pinned ones
+    ///
+    /// As a dry run, lists the buffers it would close instead.
+    pub fn close_saved_buffers(&mut self) {
+        let mut saved: Vec<(BufferId, String)> = self
+            .buffers
+            .iter()
+            .filter(|(&id, state)| !state.buffer.is_modified() && !self.is_terminal_buffer(id))
+            .filter_map(|(id, _)| {
+                let metadata = self.buffer_metadata.get(id)?;
+                metadata
+                    .file_path()
+                    .filter(|path| !path.as_os_str().is_empty())?;
+                Some((*id, metadata.display_name.clone()))
+            })
+            .collect();
+        let count = saved.len();
+        saved.retain(|(id, _)| !self.is_buffer_pinned(*id));
+        let skipped_pinned = count - saved.len();
+        saved.sort_by(|a, b| a.1.cmp(&b.1));
+
+        if self.dry_run {
+            let summary = t!("dry_run.close_saved_buffers", count = saved.len()).to_string();
+            let names = saved.into_iter().map(|(_, name)| name).collect();
+            self.report_dry_run(summary, names);
+            return;
+        }
+
+        let mut closed = 0;
+        for (buffer_id, _) in saved {
+            if self.close_buffer(buffer_id).is_ok() {
+                closed += 1;
+            }
+        }
+        let message = if closed == 0 {
+            t!("buffer.no_saved_buffers").to_string()
+        } else {
+            t!("buffer.closed_saved_buffers", count = closed).to_string()
+        };
+        self.set_status_message(with_kept_pinned(message, skipped_pinned));
     }
 
     /// Set status message for batch close operations
-    fn set_batch_close_status_message(&mut self, closed: usize, skipped_modified: usize) {
+    fn set_batch_close_status_message(
+        &mut self,
+        closed: usize,
+        skipped_modified: usize,
+        skipped_pinned: usize,
+    ) {
         let message = match (closed, skipped_modified) {
             (0, 0) => t!("buffer.no_tabs_to_close").to_string(),
             (0, n) => t!("buffer.skipped_modified", count = n).to_string(),
             (n, 0) => t!("buffer.closed_tabs", count = n).to_string(),
             (c, s) => t!("buffer.closed_tabs_skipped", closed = c, skipped = s).to_string(),
         };
-        self.set_status_message(message);
+        self.set_status_message(with_kept_pinned(message, skipped_pinned));
     }
 
     /// Close a tab silently (without setting status message)
@@ -0,0 +1,72 @@
+//! Pinned buffers
+//!
+//! A pinned buffer stays open through bulk close commands (Close Others,
+//! Close All, Close Saved Buffers, ...) and is never closed automatically,
+//! such as by the sweep of idle plugin buffers. Its tab shows a pin and the
+//! tab switcher lists it first. Pins of file buffers and persistent panels
+//! are saved in the session.
+
+use super::Editor;
+use crate::model::event::BufferId;
+use rust_i18n::t;
+
+impl Editor {
+    /// Whether a buffer is pinned
+    ///
+    /// Anything closing buffers on its own must leave pinned ones open.
+    pub fn is_buffer_pinned(&self, buffer_id: BufferId) -> bool {
+        self.buffer_metadata
+            .get(&buffer_id)
+            .is_some_and(|metadata| metadata.pinned)
+    }
+
+    /// Pin or unpin a buffer; returns false if there's no such buffer
+    ///
+    /// Nothing else about the buffer changes, unsaved edits included.
+    pub fn set_buffer_pinned(&mut self, buffer_id: BufferId, pinned: bool) -> bool {
+        match self.buffer_metadata.get_mut(&buffer_id) {
+            Some(metadata) => {
+                metadata.pinned = pinned;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Pin the active buffer, or unpin it if it's pinned
+    pub fn toggle_pin_buffer(&mut self) {
+        let buffer_id = self.active_buffer();
+        let pinned = !self.is_buffer_pinned(buffer_id);
+        if !self.set_buffer_pinned(buffer_id, pinned) {
+            return;
+        }
+        let name = self.get_buffer_display_name(buffer_id);
+        let message = if pinned {
+            t!("buffer.pinned", name = name)
+        } else {
+            t!("buffer.unpinned", name = name)
+        };
+        self.set_status_message(message.to_string());
+    }
+
+    /// Split `buffers` into those a bulk close may close and the number of
+    /// pinned ones it must skip
+    pub(super) fn without_pinned(&self, buffers: Vec<BufferId>) -> (Vec<BufferId>, usize) {
+        let count = buffers.len();
+        let unpinned: Vec<BufferId> = buffers
+            .into_iter()
+            .filter(|&id| !self.is_buffer_pinned(id))
+            .collect();
+        let skipped = count - unpinned.len();
+        (unpinned, skipped)
+    }
+}
+
+/// `message` with a note that `count` pinned buffers were left open
+pub(super) fn with_kept_pinned(message: String, count: usize) -> String {
+    if count == 0 {
+        message
+    } else {
+        t!("buffer.kept_pinned", message = message, count = count).to_string()
+    }
+}
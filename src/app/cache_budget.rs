@@ -0,0 +1,115 @@
+//! Keeping the editor's caches within `performance.cache_budget_mb`
+//!
+//! The syntax highlighting caches of the buffers and the views splits keep
+//! of buffers they showed before share one [`CacheBudget`]. It is checked
+//! once a second from the editor's tick, and "Free Caches" shrinks them all
+//! as far as they go.
+
+use std::collections::HashSet;
+use std::time::{Duration, Instant};
+
+use rust_i18n::t;
+
+use super::Editor;
+use crate::model::event::{BufferId, SplitId};
+use crate::services::cache_budget::{BudgetedEntry, CacheBudget, CacheStats};
+use crate::state::EditorState;
+use crate::view::split::SplitViewState;
+
+/// Time between two checks of the caches' sizes
+const CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// The budget and when it was last checked
+#[derive(Debug)]
+pub(super) struct CacheBudgetState {
+    budget: CacheBudget,
+    /// `performance.cache_budget_mb` the budget was computed from
+    configured_mb: u64,
+    last_check: Option<Instant>,
+}
+
+impl CacheBudgetState {
+    pub(super) fn new(cache_budget_mb: u64) -> Self {
+        Self {
+            budget: CacheBudget::new(CacheBudget::budget_from_config(cache_budget_mb)),
+            configured_mb: cache_budget_mb,
+            last_check: None,
+        }
+    }
+}
+
+/// Every cache sharing the budget; the highlight caches of the buffers on
+/// screen count as in view
+fn budgeted_caches<'a>(
+    buffers: &'a mut std::collections::HashMap<BufferId, EditorState>,
+    split_view_states: &'a mut std::collections::HashMap<SplitId, SplitViewState>,
+    visible: &HashSet<BufferId>,
+) -> Vec<BudgetedEntry<'a>> {
+    let highlighters = buffers.iter_mut().map(|(id, state)| BudgetedEntry {
+        owner: id.0 as u64,
+        in_view: visible.contains(id),
+        cache: &mut state.highlighter,
+    });
+    // Retained views are of buffers a split isn't showing
+    let retained = split_view_states
+        .iter_mut()
+        .map(|(id, view_state)| BudgetedEntry {
+            owner: id.0 as u64,
+            in_view: false,
+            cache: &mut view_state.retained_views,
+        });
+    highlighters.chain(retained).collect()
+}
+
+impl Editor {
+    fn visible_buffer_ids(&self) -> HashSet<BufferId> {
+        self.split_manager
+            .root()
+            .leaf_split_ids()
+            .into_iter()
+            .filter_map(|split_id| self.split_manager.get_buffer_id(split_id))
+            .collect()
+    }
+
+    /// Shrink the caches if they exceed the budget, at most once per
+    /// [`CHECK_INTERVAL`]
+    pub(super) fn check_cache_budget(&mut self) {
+        let now = self.time_source.now();
+        if let Some(last) = self.cache_budget.last_check {
+            if now.saturating_duration_since(last) < CHECK_INTERVAL {
+                return;
+            }
+        }
+        self.cache_budget.last_check = Some(now);
+
+        let configured_mb = self.config.performance.cache_budget_mb;
+        if configured_mb != self.cache_budget.configured_mb {
+            let budget = CacheBudget::budget_from_config(configured_mb);
+            self.cache_budget.budget.set_budget_bytes(budget);
+            self.cache_budget.configured_mb = configured_mb;
+        }
+        let visible = self.visible_buffer_ids();
+        let mut caches = budgeted_caches(&mut self.buffers, &mut self.split_view_states, &visible);
+        self.cache_budget.budget.enforce(now, &mut caches);
+    }
+
+    /// Drop all cached highlighting and retained views
+    pub fn free_caches(&mut self) {
+        let now = self.time_source.now();
+        let visible = self.visible_buffer_ids();
+        let mut caches = budgeted_caches(&mut self.buffers, &mut self.split_view_states, &visible);
+        let freed = self.cache_budget.budget.free_all(now, &mut caches);
+        self.set_status_message(t!("cache.freed", size = freed.div_ceil(1024)).to_string());
+    }
+
+    /// Sizes and hit rates of the caches sharing the budget, as of the last
+    /// check
+    pub fn cache_stats(&self) -> Vec<CacheStats> {
+        self.cache_budget.budget.stats()
+    }
+
+    /// Bytes the caches may take together
+    pub fn cache_budget_bytes(&self) -> usize {
+        self.cache_budget.budget.budget_bytes()
+    }
+}
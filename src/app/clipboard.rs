@@ -2,16 +2,23 @@
 //!
 //! This module contains clipboard operations and multi-cursor actions:
 //! - Copy/cut/paste operations
+//! - Paste from the clipboard history and cycle paste (yank-pop)
 //! - Copy with formatting (HTML with syntax highlighting)
 //! - Multi-cursor add above/below/at next match
 
 use rust_i18n::t;
+use std::time::Duration;
 
+use crate::input::actions::new_line_indent;
 use crate::input::multi_cursor::{
     add_cursor_above, add_cursor_at_next_match, add_cursor_below, AddCursorResult,
 };
-use crate::model::event::{CursorId, Event};
+use crate::input::paste_indent::{indent_width, reindent, FirstLine};
+use crate::model::event::{BufferId, CursorId, Event};
+use crate::primitives::highlighter::HighlightCategory;
 use crate::primitives::word_navigation::{find_word_start_left, find_word_start_right};
+use crate::services::todo_scanner::knows_comments;
+use crate::view::notifications::NotificationSeverity;
 
 use super::Editor;
 
@@ -26,6 +33,21 @@ use super::Editor;
 // Both use the same clipboard storage (self.clipboard) ensuring copy/paste
 // works across buffer editing and prompt input.
 
+/// Where the last paste into a buffer went, so cycle paste can replace it
+pub(super) struct LastPaste {
+    buffer_id: BufferId,
+    /// Clipboard ring entry that was pasted (None if the text isn't in the ring)
+    ring_index: Option<usize>,
+    /// Ring generation `ring_index` refers to
+    ring_generation: u64,
+    /// Length in bytes of the text as inserted
+    len: usize,
+    /// Cursors right after the paste, each at the end of its copy of the text
+    cursors: Vec<(CursorId, usize)>,
+    /// Event log position right after the paste
+    log_index: usize,
+}
+
 impl Editor {
     /// Copy the current selection to clipboard
     ///
@@ -118,7 +140,10 @@ impl Editor {
         use crate::services::styled_html::render_styled_html;
 
         // Load the requested theme
-        let theme = crate::view::theme::Theme::from_name(theme_name);
+        let theme = crate::view::theme::Theme::from_name_in(
+            theme_name,
+            Some(&self.dir_context.themes_dir()),
+        );
 
         // Collect ranges and their byte offsets
         let ranges: Vec<_> = {
@@ -188,6 +213,7 @@ impl Editor {
                         Some(crate::primitives::highlighter::HighlightSpan {
                             range: start..end,
                             color: span.color,
+                            category: span.category,
                         })
                     } else {
                         None
@@ -215,7 +241,8 @@ impl Editor {
     fn start_copy_with_formatting_prompt(&mut self) {
         use crate::view::prompt::PromptType;
 
-        let available_themes = crate::view::theme::Theme::available_themes();
+        let available_themes =
+            crate::view::theme::Theme::available_themes_in(Some(&self.dir_context.themes_dir()));
         let current_theme_name = &self.theme.name;
 
         // Find the index of the current theme
@@ -363,15 +390,31 @@ impl Editor {
     /// - Multi-cursor paste (pastes at each cursor)
     /// - Selection replacement (deletes selection before inserting)
     /// - Atomic undo (single undo step for entire operation)
+    /// - Reindenting code if `clipboard.reindent_pastes` is set
     pub fn paste(&mut self) {
+        self.paste_clipboard(self.config.clipboard.reindent_pastes);
+    }
+
+    /// Paste the clipboard content at all cursor positions, reindented to
+    /// fit the line at each
+    pub fn paste_reindented(&mut self) {
+        self.paste_clipboard(true);
+    }
+
+    /// Paste the clipboard content at all cursor positions as it is
+    pub fn paste_verbatim(&mut self) {
+        self.paste_clipboard(false);
+    }
+
+    fn paste_clipboard(&mut self, reindent: bool) {
         // Get content from clipboard (tries system first, falls back to internal)
         let text = match self.clipboard.paste() {
             Some(text) => text,
             None => return,
         };
 
-        // Use paste_text which handles line ending normalization
-        self.paste_text(text);
+        // Use paste_text_with which handles line ending normalization
+        self.paste_text_with(text, reindent);
     }
 
     /// Paste text directly into the editor
@@ -382,16 +425,48 @@ impl Editor {
     /// - Multi-cursor paste (pastes at each cursor)
     /// - Selection replacement (deletes selection before inserting)
     /// - Atomic undo (single undo step for entire operation)
-    /// - Routing to prompt if one is open
+    /// - Routing to the settings modal or a prompt if one is open
+    /// - Recording text pasted from outside the editor in the clipboard
+    ///   history, if enabled
+    /// - Reindenting code if `clipboard.reindent_pastes` is set
     pub fn paste_text(&mut self, paste_text: String) {
+        self.paste_text_with(paste_text, self.config.clipboard.reindent_pastes);
+    }
+
+    fn paste_text_with(&mut self, paste_text: String, reindent: bool) {
         if paste_text.is_empty() {
             return;
         }
+        self.note_input_for_word_index();
+        if self.config.clipboard.record_external_pastes {
+            self.clipboard.ring_mut().push(&paste_text);
+        }
+        let ring_index = self.clipboard.ring().position(&paste_text);
+        self.insert_pasted_text(paste_text, ring_index, reindent);
+    }
+
+    /// Insert pasted text, remembering where it went for cycle paste
+    ///
+    /// With `reindent`, code is reindented to fit the line at each cursor,
+    /// except in buffers without syntax highlighting and inside strings.
+    fn insert_pasted_text(
+        &mut self,
+        paste_text: String,
+        ring_index: Option<usize>,
+        reindent: bool,
+    ) {
+        self.last_paste = None;
 
         // Normalize line endings: first convert all to LF, then to buffer's format
         // This handles Windows clipboard (CRLF), old Mac (CR), and Unix (LF)
         let normalized = paste_text.replace("\r\n", "\n").replace('\r', "\n");
 
+        // The settings modal captures all input while open
+        if self.settings_state.as_ref().is_some_and(|s| s.visible) {
+            self.paste_to_settings(&normalized);
+            return;
+        }
+
         // If a prompt is open, paste into the prompt (prompts use LF internally)
         if let Some(prompt) = self.prompt.as_mut() {
             prompt.insert_str(&normalized);
@@ -400,15 +475,18 @@ impl Editor {
             return;
         }
 
-        // Convert to buffer's line ending format
-        let buffer_line_ending = self.active_state().buffer.line_ending();
-        let paste_text = match buffer_line_ending {
-            crate::model::buffer::LineEnding::LF => normalized,
-            crate::model::buffer::LineEnding::CRLF => normalized.replace('\n', "\r\n"),
-            crate::model::buffer::LineEnding::CR => normalized.replace('\n', "\r"),
-        };
+        // Pasting into a locked split is refused like typing
+        if self.split_lock_blocks(&crate::input::keybindings::Action::Paste) {
+            return;
+        }
+
+        let reindent = reindent
+            && normalized.contains('\n')
+            && knows_comments(&self.active_state().highlighter);
+        let paste_text = self.with_buffer_line_endings(normalized.clone());
 
         let mut events = Vec::new();
+        let mut lens = Vec::new();
 
         // Collect cursor info sorted in reverse order by position
         let state = self.active_state();
@@ -442,6 +520,12 @@ impl Editor {
 
         // Build events for each cursor
         for (cursor_id, selection, insert_position, deleted_text) in cursor_data_with_text {
+            let inserted = if reindent && !self.inside_string(insert_position) {
+                let text = self.reindent_paste(&normalized, insert_position);
+                self.with_buffer_line_endings(text)
+            } else {
+                paste_text.clone()
+            };
             if let (Some(range), Some(text)) = (selection, deleted_text) {
                 events.push(Event::Delete {
                     range,
@@ -449,25 +533,248 @@ impl Editor {
                     cursor_id,
                 });
             }
+            lens.push(inserted.len());
             events.push(Event::Insert {
                 position: insert_position,
-                text: paste_text.clone(),
+                text: inserted,
                 cursor_id,
             });
         }
 
-        // Apply events with atomic undo using bulk edit for O(n) performance
+        self.apply_paste_events(events, &normalized);
+        // Cycle paste replaces the same length at every cursor
+        if let Some(&len) = lens.first().filter(|len| lens.iter().all(|l| l == *len)) {
+            self.remember_paste(ring_index, len);
+        }
+        self.status_message = Some(t!("clipboard.pasted").to_string());
+    }
+
+    /// `text` reindented to fit the line of the active buffer at `position`
+    ///
+    /// On a line with only indentation before `position`, the text takes
+    /// the line's indent, or the auto-indent for a new line if the line is
+    /// blank. After other text, the first line stays as it is and the rest
+    /// take the line's indent.
+    fn reindent_paste(&mut self, text: &str, position: usize) -> String {
+        let estimated_line_length = self.config.editor.estimated_line_length;
+        let state = self.active_state_mut();
+        let tab_size = state.tab_size;
+        let (line_start, line) = {
+            let mut iter = state.buffer.line_iterator(position, estimated_line_length);
+            let line_start = iter.current_position();
+            let line = iter.next().map(|(_, line)| line).unwrap_or_default();
+            (line_start, line)
+        };
+        let before = &line[..(position - line_start).min(line.len())];
+        let blank_line = line.trim().is_empty();
+
+        let first_line = if before.trim().is_empty() {
+            FirstLine::AtIndent(indent_width(before, tab_size))
+        } else {
+            FirstLine::InPlace
+        };
+        let indent = if blank_line {
+            // The indent Enter at the end of the line above would give
+            let line_ending_len = state.buffer.line_ending().as_str().len();
+            match line_start.checked_sub(line_ending_len) {
+                Some(above) => new_line_indent(state, above, tab_size),
+                None => 0,
+            }
+        } else {
+            indent_width(&line, tab_size)
+        };
+        reindent(text, indent, first_line, state.use_tabs, tab_size)
+    }
+
+    /// Whether `position` in the active buffer is inside a string literal
+    fn inside_string(&mut self, position: usize) -> bool {
+        if position == 0 {
+            return false;
+        }
+        let context_bytes = self.config.editor.highlight_context_bytes;
+        let buffer_id = self.active_buffer();
+        let state = self.buffers.get_mut(&buffer_id).unwrap();
+        state
+            .highlighter
+            .highlight_viewport(
+                &state.buffer,
+                position - 1,
+                position,
+                &self.theme,
+                context_bytes,
+            )
+            .iter()
+            .any(|span| {
+                span.category == Some(HighlightCategory::String)
+                    && span.range.start < position
+                    && position < span.range.end
+            })
+    }
+
+    /// Convert LF line endings to the active buffer's format
+    pub(super) fn with_buffer_line_endings(&self, text: String) -> String {
+        match self.active_state().buffer.line_ending() {
+            crate::model::buffer::LineEnding::LF => text,
+            crate::model::buffer::LineEnding::CRLF => text.replace('\n', "\r\n"),
+            crate::model::buffer::LineEnding::CR => text.replace('\n', "\r"),
+        }
+    }
+
+    /// Apply the edits of a paste of `text` with atomic undo
+    fn apply_paste_events(&mut self, events: Vec<Event>, text: &str) {
+        let label = t!("undo.paste", count = text.lines().count().max(1)).to_string();
         if events.len() > 1 {
             // Use optimized bulk edit for multi-cursor paste
-            if let Some(bulk_edit) = self.apply_events_as_bulk_edit(events, "Paste".to_string()) {
-                self.active_event_log_mut().append(bulk_edit);
+            if let Some(bulk_edit) =
+                self.apply_cursor_events_as_bulk_edit(events, "Paste".to_string())
+            {
+                self.active_event_log_mut().append_labeled(bulk_edit, label);
             }
         } else if let Some(event) = events.into_iter().next() {
-            self.active_event_log_mut().append(event.clone());
+            self.active_event_log_mut()
+                .append_labeled(event.clone(), label);
             self.apply_event_to_active_buffer(&event);
         }
+    }
 
-        self.status_message = Some(t!("clipboard.pasted").to_string());
+    /// Record the paste that just happened in the active buffer
+    fn remember_paste(&mut self, ring_index: Option<usize>, len: usize) {
+        let buffer_id = self.active_buffer();
+        let cursors = self.paste_cursors();
+        let log_index = self.active_event_log().current_index();
+        self.last_paste = Some(LastPaste {
+            buffer_id,
+            ring_index,
+            ring_generation: self.clipboard.ring().generation(),
+            len,
+            cursors,
+            log_index,
+        });
+    }
+
+    /// Cursor positions of the active buffer, in a stable order
+    fn paste_cursors(&self) -> Vec<(CursorId, usize)> {
+        let mut cursors: Vec<_> = self
+            .active_state()
+            .cursors
+            .iter()
+            .map(|(id, cursor)| (id, cursor.position))
+            .collect();
+        cursors.sort_by_key(|(_, position)| *position);
+        cursors
+    }
+
+    /// Whether nothing has happened in the buffer since `last` was pasted
+    fn is_latest_paste(&self, last: &LastPaste) -> bool {
+        last.buffer_id == self.active_buffer()
+            && last.ring_generation == self.clipboard.ring().generation()
+            && last.log_index == self.active_event_log().current_index()
+            && last.cursors == self.paste_cursors()
+            && last
+                .cursors
+                .iter()
+                .all(|(_, position)| *position >= last.len)
+    }
+
+    /// Replace the text just pasted with the next older clipboard history entry
+    ///
+    /// Only works right after a paste (or a previous cycle); otherwise says so
+    /// in the status bar. The replacement at every cursor is one undo step.
+    pub fn cycle_paste(&mut self) {
+        let last = match self.last_paste.take() {
+            Some(last) if self.is_latest_paste(&last) => last,
+            _ => {
+                self.set_status_message(t!("clipboard.cycle_needs_paste").to_string());
+                return;
+            }
+        };
+        let ring = self.clipboard.ring();
+        if ring.is_empty() {
+            self.set_status_message(t!("clipboard.history_empty").to_string());
+            return;
+        }
+        let count = ring.len();
+        let index = last.ring_index.map_or(0, |i| (i + 1) % count);
+        let Some(text) = ring.get(index).map(|entry| entry.text().to_string()) else {
+            return;
+        };
+        let text = self.with_buffer_line_endings(text.replace("\r\n", "\n").replace('\r', "\n"));
+        let len = text.len();
+
+        let state = self.active_state_mut();
+        let mut events = Vec::new();
+        for (cursor_id, position) in last.cursors.iter().rev() {
+            let range = position - last.len..*position;
+            events.push(Event::Delete {
+                deleted_text: state.get_text_range(range.start, range.end),
+                range: range.clone(),
+                cursor_id: *cursor_id,
+            });
+            events.push(Event::Insert {
+                position: range.start,
+                text: text.clone(),
+                cursor_id: *cursor_id,
+            });
+        }
+
+        self.apply_paste_events(events, &text);
+        self.remember_paste(Some(index), len);
+        self.set_status_message(
+            t!("clipboard.cycled", index = index + 1, count = count).to_string(),
+        );
+    }
+
+    /// Show a picker of the clipboard history; the chosen entry is pasted at all cursors
+    pub fn start_paste_from_history_prompt(&mut self) {
+        let ring = self.clipboard.ring();
+        if ring.is_empty() {
+            self.set_status_message(t!("clipboard.history_empty").to_string());
+            return;
+        }
+        let suggestions: Vec<crate::input::commands::Suggestion> = ring
+            .iter()
+            .enumerate()
+            .map(|(index, entry)| crate::input::commands::Suggestion {
+                text: entry.preview().to_string(),
+                description: Some(
+                    t!(
+                        "clipboard.history_entry_size",
+                        chars = entry.text().chars().count(),
+                        lines = entry.text().lines().count().max(1)
+                    )
+                    .to_string(),
+                ),
+                value: Some(index.to_string()),
+                disabled: false,
+                keybinding: None,
+                source: None,
+            })
+            .collect();
+
+        self.prompt = Some(crate::view::prompt::Prompt::with_suggestions(
+            t!("clipboard.history_prompt").to_string(),
+            crate::view::prompt::PromptType::PasteFromHistory,
+            suggestions,
+        ));
+        if let Some(prompt) = self.prompt.as_mut() {
+            prompt.selected_suggestion = Some(0);
+        }
+    }
+
+    /// Paste the clipboard history entry chosen in the picker
+    pub(super) fn paste_from_history(&mut self, input: &str) {
+        let Some(index) = input.trim().parse::<usize>().ok() else {
+            return;
+        };
+        let Some(text) = self
+            .clipboard
+            .ring()
+            .get(index)
+            .map(|entry| entry.text().to_string())
+        else {
+            return;
+        };
+        self.insert_pasted_text(text, Some(index), self.config.clipboard.reindent_pastes);
     }
 
     /// Set clipboard content for testing purposes
@@ -475,10 +782,41 @@ impl Editor {
     /// system clipboard interference between parallel tests
     #[doc(hidden)]
     pub fn set_clipboard_for_test(&mut self, text: String) {
+        self.clipboard.ring_mut().push(&text);
         self.clipboard.set_internal(text);
         self.clipboard.set_internal_only(true);
     }
 
+    /// Apply the clipboard history limits and system clipboard timeout from the config
+    pub(super) fn apply_clipboard_limits(&mut self) {
+        let config = &self.config.clipboard;
+        self.clipboard
+            .ring_mut()
+            .set_limits(config.history_size, config.history_max_bytes);
+        self.clipboard
+            .set_system_timeout(Duration::from_millis(config.system_timeout_ms));
+    }
+
+    /// Warn (once per session) when the system clipboard stopped answering
+    pub(super) fn report_slow_clipboard(&mut self) {
+        if let Some(backend) = self.clipboard.take_slow_warning() {
+            self.notify(
+                NotificationSeverity::Warning,
+                t!(
+                    "clipboard.system_slow",
+                    backend = backend,
+                    ms = self.config.clipboard.system_timeout_ms
+                ),
+            );
+        }
+    }
+
+    /// Use the system clipboard again after it timed out
+    pub fn retry_system_clipboard(&mut self) {
+        self.clipboard.retry_system();
+        self.set_status_message(t!("clipboard.system_retry").to_string());
+    }
+
     /// Paste from internal clipboard only (for testing)
     /// This bypasses the system clipboard to avoid interference from CI environments
     #[doc(hidden)]
@@ -0,0 +1,255 @@
+//! "Paste as Column" and "Align Selections by Character"
+//!
+//! Paste as Column inserts the clipboard's lines one per cursor, or, with a
+//! single cursor, down the lines below it at the cursor's column. Align
+//! Selections pads the selected lines (or each cursor's line) so the first
+//! occurrence of a string lines up across them. Both only insert text, in
+//! one undo step; the column arithmetic is in
+//! [`crate::primitives::column_align`].
+
+use super::Editor;
+use crate::input::keybindings::Action;
+use crate::model::buffer::Buffer;
+use crate::model::event::Event;
+use crate::primitives::column_align::{alignment_padding, column_after, insertion_point};
+use crate::view::prompt::PromptType;
+use rust_i18n::t;
+
+impl Editor {
+    /// Paste the clipboard's lines at the cursors, one line per cursor
+    ///
+    /// With a single cursor, line k goes on the k-th line below it, at the
+    /// cursor's visual column, padding short lines with spaces. Lines past
+    /// the end of the buffer are added.
+    pub fn paste_as_column(&mut self) {
+        if self.edit_blocked(&Action::PasteAsColumn) {
+            return;
+        }
+        let Some(text) = self.clipboard.paste() else {
+            return;
+        };
+        let text = text.replace("\r\n", "\n").replace('\r', "\n");
+        let mut pieces: Vec<&str> = text.split('\n').collect();
+        if pieces.len() > 1 && pieces.last() == Some(&"") {
+            pieces.pop();
+        }
+
+        let state = self.active_state_mut();
+        let tab_size = state.tab_size;
+        let line_ending = state.buffer.line_ending().as_str();
+        let mut positions: Vec<usize> = state
+            .cursors
+            .iter()
+            .map(|(_, cursor)| cursor.position)
+            .collect();
+        positions.sort_unstable();
+
+        // (position, text) in buffer order
+        let mut inserts: Vec<(usize, String)> = Vec::new();
+        if positions.len() > 1 {
+            for (&position, piece) in positions.iter().zip(&pieces) {
+                inserts.push((position, piece.to_string()));
+            }
+        } else {
+            let position = positions[0];
+            let line_start = line_start(&mut state.buffer, position);
+            let column = column_after(&state.get_text_range(line_start, position), tab_size);
+            let lines = following_lines(&mut state.buffer, line_start, pieces.len());
+            for ((start, content), piece) in lines.iter().zip(&pieces) {
+                let (offset, pad) = insertion_point(content, column, tab_size);
+                inserts.push((start + offset, format!("{}{}", " ".repeat(pad), piece)));
+            }
+            // Lines below the end of the buffer
+            let buffer_len = state.buffer.len();
+            let mut at_line_start =
+                buffer_len == 0 || state.buffer.slice_bytes(buffer_len - 1..buffer_len) == b"\n";
+            let mut appended = String::new();
+            for piece in &pieces[lines.len()..] {
+                if !at_line_start {
+                    appended.push_str(line_ending);
+                }
+                at_line_start = false;
+                appended.push_str(&" ".repeat(column));
+                appended.push_str(piece);
+            }
+            if !appended.is_empty() {
+                inserts.push((buffer_len, appended));
+            }
+        }
+
+        self.insert_column_text(inserts, false, t!("action.paste_as_column").to_string());
+    }
+
+    /// Ask for the string to line up, then align
+    pub fn start_align_selections_prompt(&mut self) {
+        if self.edit_blocked(&Action::AlignSelections) {
+            return;
+        }
+        self.start_prompt(
+            t!("column.align_prompt").to_string(),
+            PromptType::AlignSelections,
+        );
+    }
+
+    /// Pad the selected lines, or each cursor's line, so the first
+    /// `delimiter` of each starts at the same visual column
+    pub fn align_selections(&mut self, delimiter: &str) {
+        if delimiter.is_empty() {
+            return;
+        }
+        let state = self.active_state_mut();
+        let tab_size = state.tab_size;
+        let mut starts: Vec<usize> = Vec::new();
+        let ranges: Vec<(usize, usize)> = state
+            .cursors
+            .iter()
+            .map(|(_, cursor)| {
+                cursor
+                    .selection_range()
+                    .map_or((cursor.position, cursor.position), |range| {
+                        // A selection ending at the start of a line doesn't
+                        // include that line
+                        let end = if range.end > range.start {
+                            range.end - 1
+                        } else {
+                            range.end
+                        };
+                        (range.start, end)
+                    })
+            })
+            .collect();
+        for (start, end) in ranges {
+            let mut iter = state.buffer.line_iterator(start, 80);
+            while let Some((offset, _)) = iter.next() {
+                if offset > end {
+                    break;
+                }
+                starts.push(offset);
+            }
+        }
+        starts.sort_unstable();
+        starts.dedup();
+
+        let lines: Vec<(usize, String)> = starts
+            .into_iter()
+            .filter_map(|start| {
+                let (_, text) = state.buffer.line_iterator(start, 80).next()?;
+                Some((start, text.trim_end_matches(['\n', '\r']).to_string()))
+            })
+            .collect();
+        let contents: Vec<&str> = lines.iter().map(|(_, text)| text.as_str()).collect();
+        let padding = alignment_padding(&contents, delimiter, tab_size);
+
+        let skipped = padding.iter().filter(|pad| pad.is_none()).count();
+        if skipped == lines.len() {
+            self.set_status_message(t!("column.align_no_match", delimiter = delimiter).to_string());
+            return;
+        }
+        let inserts: Vec<(usize, String)> = lines
+            .iter()
+            .zip(&padding)
+            .filter_map(|((start, _), pad)| {
+                let (offset, spaces) = (*pad)?;
+                (spaces > 0).then(|| (start + offset, " ".repeat(spaces)))
+            })
+            .collect();
+        if inserts.is_empty() {
+            self.set_status_message(
+                t!("column.align_unchanged", delimiter = delimiter).to_string(),
+            );
+            return;
+        }
+
+        self.insert_column_text(inserts, true, t!("action.align_selections").to_string());
+        if skipped > 0 {
+            self.set_status_message(
+                t!(
+                    "column.align_skipped",
+                    count = skipped,
+                    delimiter = delimiter
+                )
+                .to_string(),
+            );
+        }
+    }
+
+    /// Insert each `(position, text)`, in buffer order, as one undo step
+    ///
+    /// Cursors move with the text inserted at or before them. With
+    /// `keep_selections` their selections move along; otherwise they're
+    /// cleared.
+    fn insert_column_text(
+        &mut self,
+        inserts: Vec<(usize, String)>,
+        keep_selections: bool,
+        description: String,
+    ) {
+        if inserts.is_empty() {
+            return;
+        }
+        let state = self.active_state();
+        let primary_id = state.cursors.primary_id();
+        let shifted = |offset: usize| {
+            offset
+                + inserts
+                    .iter()
+                    .filter(|(position, _)| *position <= offset)
+                    .map(|(_, text)| text.len())
+                    .sum::<usize>()
+        };
+
+        // From the end, so earlier inserts don't move later positions
+        let mut events: Vec<Event> = inserts
+            .iter()
+            .rev()
+            .map(|(position, text)| Event::Insert {
+                position: *position,
+                text: text.clone(),
+                cursor_id: primary_id,
+            })
+            .collect();
+        for (cursor_id, cursor) in state.cursors.iter() {
+            let new_anchor = if keep_selections {
+                cursor.anchor.map(shifted)
+            } else {
+                None
+            };
+            events.push(Event::MoveCursor {
+                cursor_id,
+                old_position: cursor.position,
+                new_position: shifted(cursor.position),
+                old_anchor: cursor.anchor,
+                new_anchor,
+                old_sticky_column: cursor.sticky_column,
+                new_sticky_column: 0,
+            });
+        }
+
+        if let Some(bulk_edit) = self.apply_events_as_bulk_edit(events, description.clone()) {
+            self.active_event_log_mut()
+                .append_labeled(bulk_edit, description);
+        }
+    }
+}
+
+/// Start of the line holding `offset`
+fn line_start(buffer: &mut Buffer, offset: usize) -> usize {
+    buffer
+        .line_iterator(offset, 80)
+        .next()
+        .map_or(offset, |(start, _)| start)
+}
+
+/// The first `count` lines from `start` that are in the buffer, as their
+/// start and their text without the line ending
+fn following_lines(buffer: &mut Buffer, start: usize, count: usize) -> Vec<(usize, String)> {
+    let mut iter = buffer.line_iterator(start, 80);
+    let mut lines = Vec::with_capacity(count);
+    while lines.len() < count {
+        let Some((line_start, text)) = iter.next() else {
+            break;
+        };
+        lines.push((line_start, text.trim_end_matches(['\n', '\r']).to_string()));
+    }
+    lines
+}
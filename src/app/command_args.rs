@@ -0,0 +1,296 @@
+//! Running commands with typed arguments
+//!
+//! A plugin command with declared parameters (see
+//! [`crate::input::command_args`]) gets their values from one of three
+//! places: a prompt per parameter when it is picked in the command palette,
+//! the `args` of a `run_command` key binding, or the words after its name
+//! when typed as `:Name value…` in the palette. `Editor::command_args`
+//! holds the values while the action is handled, and the plugin action gets
+//! them in `options.args`.
+
+use rust_i18n::t;
+use serde_json::{Map, Value};
+
+use super::prompt_actions::PromptResult;
+use super::Editor;
+use crate::input::command_args::{self, CommandArg, CommandArgType};
+use crate::input::commands::{Command, Suggestion};
+use crate::input::fuzzy::fuzzy_match;
+use crate::input::keybindings::Action;
+use crate::view::prompt::PromptType;
+
+/// Most entries offered when completing a path parameter
+const MAX_PATH_SUGGESTIONS: usize = 100;
+
+impl Editor {
+    /// Command named `name`, by its name or its localized name
+    fn find_command(&self, name: &str) -> Option<Command> {
+        let registry = self.command_registry.read().unwrap();
+        registry.find_by_name(name).or_else(|| {
+            registry
+                .get_all()
+                .into_iter()
+                .find(|c| c.get_localized_name() == name)
+        })
+    }
+
+    /// Handle `action` with `args` as the values of its parameters
+    pub(super) fn handle_action_with_args(
+        &mut self,
+        action: Action,
+        args: Map<String, Value>,
+        dry_run: bool,
+    ) -> std::io::Result<()> {
+        self.command_args = Some(args);
+        let result = if dry_run {
+            self.handle_action_dry_run(action)
+        } else {
+            self.handle_action(action)
+        };
+        self.command_args = None;
+        result
+    }
+
+    /// Run the command named `name` with the values of its parameters given
+    /// by name, as a `run_command` key binding does
+    pub fn run_command_with_args(
+        &mut self,
+        name: &str,
+        values: &Map<String, Value>,
+    ) -> std::io::Result<()> {
+        let Some(command) = self.find_command(name) else {
+            self.set_status_message(t!("error.unknown_command", input = name).to_string());
+            return Ok(());
+        };
+        let declared = self.declared_args(&command);
+        let args = command_args::check_named(&declared, values, &self.working_dir);
+        match self.command_with_args(&command, &declared, args) {
+            PromptResult::ExecuteAction(action) => self.handle_action(action),
+            PromptResult::ExecuteActionWithArgs(action, args) => {
+                self.handle_action_with_args(action, args, false)
+            }
+            PromptResult::Done | PromptResult::EarlyReturn => Ok(()),
+        }
+    }
+
+    /// Run `:Name value…` typed in the command palette (without the `:`)
+    pub(super) fn run_command_line(&mut self, line: &str) -> PromptResult {
+        let line = line.trim();
+        let names: Vec<String> = self
+            .command_registry
+            .read()
+            .unwrap()
+            .get_all()
+            .iter()
+            .map(|c| c.get_localized_name())
+            .collect();
+        let command = command_args::split_command_line(line, names.iter().map(String::as_str))
+            .and_then(|(name, rest)| Some((self.find_command(name)?, rest)));
+        let Some((command, rest)) = command else {
+            self.set_status_message(t!("error.unknown_command", input = line).to_string());
+            return PromptResult::Done;
+        };
+        let declared = self.declared_args(&command);
+        let args = command_args::parse_positional(&declared, rest, &self.working_dir);
+        self.command_with_args(&command, &declared, args)
+    }
+
+    /// Prompt for the first parameter of `command`, if it declares any
+    pub(super) fn start_command_args(&mut self, command: &Command) -> bool {
+        let declared = self.declared_args(command);
+        if declared.is_empty() {
+            return false;
+        }
+        self.prompt_command_arg(command, &declared, 0, Map::new(), String::new());
+        true
+    }
+
+    /// Take the value typed for parameter `index` of `command`, then prompt
+    /// for the next one or run the command
+    pub(super) fn handle_command_arg_input(
+        &mut self,
+        command: &str,
+        index: usize,
+        mut values: Map<String, Value>,
+        input: String,
+        selected_index: Option<usize>,
+    ) -> PromptResult {
+        let Some(command) = self.find_command(command) else {
+            self.set_status_message(t!("error.unknown_command", input = command).to_string());
+            return PromptResult::Done;
+        };
+        let declared = self.declared_args(&command);
+        let Some(arg) = declared.get(index) else {
+            return PromptResult::Done;
+        };
+        // A partly typed choice confirms the highlighted match
+        let text = match (&arg.arg_type, selected_index) {
+            (CommandArgType::Choice(choices), Some(i)) if !choices.contains(&input) => {
+                matching_choices(choices, &input)
+                    .get(i)
+                    .map_or_else(|| input.clone(), |choice| choice.to_string())
+            }
+            _ => input.clone(),
+        };
+        match arg.parse(&text, &self.working_dir) {
+            Ok(value) => {
+                if let Some(value) = value {
+                    values.insert(arg.name.clone(), value);
+                }
+                if index + 1 < declared.len() {
+                    self.prompt_command_arg(&command, &declared, index + 1, values, String::new());
+                    return PromptResult::Done;
+                }
+                self.command_with_args(&command, &declared, Ok(values))
+            }
+            Err(error) => {
+                self.show_command_args_error(&command, error);
+                self.prompt_command_arg(&command, &declared, index, values, input);
+                PromptResult::Done
+            }
+        }
+    }
+
+    /// Offer the choices or the paths matching `input` for parameter
+    /// `index` of `command`
+    pub(super) fn update_command_arg_suggestions(
+        &mut self,
+        command: &str,
+        index: usize,
+        input: &str,
+    ) {
+        let arg = self
+            .find_command(command)
+            .and_then(|command| self.declared_args(&command).get(index).cloned());
+        let suggestions = match arg.map(|arg| arg.arg_type) {
+            Some(CommandArgType::Choice(choices)) => matching_choices(&choices, input)
+                .into_iter()
+                .map(|choice| Suggestion::new(choice.to_string()))
+                .collect(),
+            Some(CommandArgType::Path) => self.path_suggestions(input),
+            _ => Vec::new(),
+        };
+        if let Some(prompt) = &mut self.prompt {
+            prompt.selected_suggestion = if suggestions.is_empty() {
+                None
+            } else {
+                Some(0)
+            };
+            prompt.suggestions = suggestions;
+        }
+    }
+
+    fn declared_args(&self, command: &Command) -> Vec<CommandArg> {
+        self.command_registry
+            .read()
+            .unwrap()
+            .action_args(&command.action)
+    }
+
+    fn prompt_command_arg(
+        &mut self,
+        command: &Command,
+        declared: &[CommandArg],
+        index: usize,
+        values: Map<String, Value>,
+        text: String,
+    ) {
+        let arg = &declared[index];
+        let name = command.get_localized_name();
+        let message = if arg.optional {
+            t!(
+                "command_args.prompt_optional",
+                command = &name,
+                name = &arg.name
+            )
+        } else {
+            t!("command_args.prompt", command = &name, name = &arg.name)
+        };
+        self.start_prompt_with_initial_text(
+            message.to_string(),
+            PromptType::CommandArgument {
+                command: command.name.clone(),
+                index,
+                values,
+            },
+            text,
+        );
+        self.update_prompt_suggestions();
+    }
+
+    /// What to run for `command` once its parameters have `args`, or nothing
+    /// if they are invalid
+    fn command_with_args(
+        &mut self,
+        command: &Command,
+        declared: &[CommandArg],
+        args: Result<Map<String, Value>, String>,
+    ) -> PromptResult {
+        let args = match args {
+            Ok(args) => args,
+            Err(error) => {
+                self.show_command_args_error(command, error);
+                return PromptResult::Done;
+            }
+        };
+        let name = command.get_localized_name();
+        self.set_status_message(t!("error.executing", cmd = &name).to_string());
+        self.command_registry.write().unwrap().record_usage(&name);
+        if declared.is_empty() {
+            PromptResult::ExecuteAction(command.action.clone())
+        } else {
+            PromptResult::ExecuteActionWithArgs(command.action.clone(), args)
+        }
+    }
+
+    fn show_command_args_error(&mut self, command: &Command, error: String) {
+        self.set_status_message(
+            t!(
+                "command_args.invalid",
+                command = command.get_localized_name(),
+                error = error
+            )
+            .to_string(),
+        );
+    }
+
+    /// Entries of the directory typed so far whose names start with the
+    /// rest of `input`; hidden ones only once a `.` is typed
+    fn path_suggestions(&self, input: &str) -> Vec<Suggestion> {
+        let split = input
+            .rfind(['/', std::path::MAIN_SEPARATOR])
+            .map_or(0, |i| i + 1);
+        let (dir, prefix) = input.split_at(split);
+        let Ok(entries) = std::fs::read_dir(self.working_dir.join(dir)) else {
+            return Vec::new();
+        };
+        let mut paths: Vec<String> = entries
+            .flatten()
+            .filter_map(|entry| {
+                let name = entry.file_name().to_string_lossy().into_owned();
+                if !name.starts_with(prefix) || (name.starts_with('.') && !prefix.starts_with('.'))
+                {
+                    return None;
+                }
+                let slash = if entry.file_type().is_ok_and(|t| t.is_dir()) {
+                    "/"
+                } else {
+                    ""
+                };
+                Some(format!("{}{}{}", dir, name, slash))
+            })
+            .collect();
+        paths.sort();
+        paths.truncate(MAX_PATH_SUGGESTIONS);
+        paths.into_iter().map(Suggestion::new).collect()
+    }
+}
+
+/// Choices matching what was typed, in their declared order
+fn matching_choices<'a>(choices: &'a [String], input: &str) -> Vec<&'a str> {
+    choices
+        .iter()
+        .filter(|choice| fuzzy_match(input, choice).matched)
+        .map(String::as_str)
+        .collect()
+}
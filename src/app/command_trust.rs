@@ -0,0 +1,242 @@
+//! Asking the user about commands named by the project's config
+//!
+//! [`CommandGate`](crate::services::command_trust::CommandGate) holds back
+//! project commands nobody has decided on. This module asks about them one
+//! at a time in a popup showing the command line and the config file that
+//! names it, and lets the user revisit blocked commands later.
+
+use rust_i18n::t;
+
+use super::Editor;
+use crate::model::event::{PopupContentData, PopupData, PopupListItemData, PopupPositionData};
+use crate::services::command_trust::{CommandUse, ProjectCommand, TrustDecision};
+
+/// Popup item data for "Allow once"
+const ALLOW_ONCE_ACTION: &str = "allow_once";
+/// Popup item data for "Always for this project"
+const ALWAYS_ACTION: &str = "always";
+/// Popup item data for "Never"
+const NEVER_ACTION: &str = "never";
+
+/// A project command waiting on the trust popup
+#[derive(Debug)]
+pub(super) struct PendingCommandTrust {
+    command: ProjectCommand,
+    /// Title of the popup, used to recognize it
+    popup_title: String,
+}
+
+impl Editor {
+    /// Ask about the oldest project command waiting for a decision
+    ///
+    /// Returns true if a popup was opened and the screen needs a redraw.
+    pub fn check_command_trust_requests(&mut self) -> bool {
+        if self.pending_command_trust.is_some() {
+            if self.is_command_trust_popup() {
+                return false;
+            }
+            // Closed some other way: ask again next session
+            if let Some(pending) = self.pending_command_trust.take() {
+                self.command_gate.postpone(&pending.command.command_line);
+            }
+        }
+        match self.command_gate.next_pending() {
+            Some(command) => {
+                self.show_command_trust_popup(command);
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn show_command_trust_popup(&mut self, command: ProjectCommand) {
+        let title = t!("command_trust.title").to_string();
+        let item = |text: String, detail: String, data: &str| PopupListItemData {
+            text,
+            detail: Some(detail),
+            icon: None,
+            data: Some(data.to_string()),
+        };
+        let popup = PopupData {
+            title: Some(title.clone()),
+            description: Some(
+                t!(
+                    "command_trust.description",
+                    command = command.command_line.clone(),
+                    origin = command.origin.display().to_string()
+                )
+                .to_string(),
+            ),
+            transient: false,
+            content: PopupContentData::List {
+                items: vec![
+                    item(
+                        t!("command_trust.allow_once").to_string(),
+                        t!("command_trust.allow_once_detail").to_string(),
+                        ALLOW_ONCE_ACTION,
+                    ),
+                    item(
+                        t!("command_trust.always").to_string(),
+                        t!("command_trust.always_detail").to_string(),
+                        ALWAYS_ACTION,
+                    ),
+                    item(
+                        t!("command_trust.never").to_string(),
+                        t!("command_trust.never_detail").to_string(),
+                        NEVER_ACTION,
+                    ),
+                ],
+                selected: 0,
+            },
+            position: PopupPositionData::Centered,
+            width: 70,
+            max_height: 10,
+            bordered: true,
+        };
+
+        self.pending_command_trust = Some(PendingCommandTrust {
+            command,
+            popup_title: title,
+        });
+        self.show_popup(popup);
+    }
+
+    /// Whether the topmost popup is the trust popup
+    pub(super) fn is_command_trust_popup(&self) -> bool {
+        let Some(pending) = &self.pending_command_trust else {
+            return false;
+        };
+        self.active_state()
+            .popups
+            .top()
+            .and_then(|popup| popup.title.as_ref())
+            .is_some_and(|title| *title == pending.popup_title)
+    }
+
+    /// Handle the user's choice in the trust popup
+    ///
+    /// `None` means the popup was dismissed: the command stays held back and
+    /// is asked about again next session.
+    pub(super) fn handle_command_trust_response(&mut self, action: Option<&str>) {
+        let Some(pending) = self.pending_command_trust.take() else {
+            return;
+        };
+        let command = pending.command;
+        let decision = match action {
+            Some(ALLOW_ONCE_ACTION) => TrustDecision::AllowOnce,
+            Some(ALWAYS_ACTION) => TrustDecision::Always,
+            Some(NEVER_ACTION) => TrustDecision::Never,
+            _ => {
+                self.command_gate.postpone(&command.command_line);
+                self.set_status_message(
+                    t!(
+                        "command_trust.postponed",
+                        command = command.command_line.clone()
+                    )
+                    .to_string(),
+                );
+                return;
+            }
+        };
+
+        if let Err(e) = self.command_gate.decide(&command.command_line, decision) {
+            tracing::warn!("Failed to save command trust decision: {}", e);
+            self.set_status_message(
+                t!("command_trust.save_failed", error = e.to_string()).to_string(),
+            );
+            return;
+        }
+
+        if decision == TrustDecision::Never {
+            self.set_status_message(
+                t!("command_trust.blocked", command = command.command_line).to_string(),
+            );
+            return;
+        }
+        self.set_status_message(
+            t!(
+                "command_trust.allowed",
+                command = command.command_line.clone()
+            )
+            .to_string(),
+        );
+        // Servers start now; formatters and on-save actions run on the next save
+        for usage in &command.uses {
+            if let CommandUse::LspServer(language) = usage {
+                self.reopen_buffers_for_language(language);
+            }
+        }
+    }
+
+    /// Number of project commands that are blocked or put off
+    pub fn blocked_command_count(&self) -> usize {
+        self.command_gate.held_back().len()
+    }
+
+    /// List the blocked and put-off project commands; choosing one asks
+    /// about it again
+    pub fn review_blocked_commands(&mut self) {
+        let commands = self.command_gate.held_back();
+        if commands.is_empty() {
+            self.set_status_message(t!("command_trust.none_blocked").to_string());
+            return;
+        }
+        let title = t!("command_trust.review_title").to_string();
+        let items = commands
+            .into_iter()
+            .map(|command| {
+                let state = if command.blocked {
+                    t!("command_trust.state_blocked")
+                } else {
+                    t!("command_trust.state_postponed")
+                };
+                PopupListItemData {
+                    text: command.command_line.clone(),
+                    detail: Some(format!("{} · {}", state, command.origin.display())),
+                    icon: None,
+                    data: Some(command.command_line),
+                }
+            })
+            .collect();
+        let popup = PopupData {
+            title: Some(title.clone()),
+            description: Some(t!("command_trust.review_description").to_string()),
+            transient: false,
+            content: PopupContentData::List { items, selected: 0 },
+            position: PopupPositionData::Centered,
+            width: 80,
+            max_height: 15,
+            bordered: true,
+        };
+        self.blocked_commands_review = Some(title);
+        self.show_popup(popup);
+    }
+
+    /// Whether the topmost popup is the blocked commands review
+    pub(super) fn is_blocked_commands_popup(&self) -> bool {
+        let Some(review_title) = &self.blocked_commands_review else {
+            return false;
+        };
+        self.active_state()
+            .popups
+            .top()
+            .and_then(|popup| popup.title.as_ref())
+            .is_some_and(|title| title == review_title)
+    }
+
+    /// Handle a command chosen in the review (`None` when dismissed)
+    pub(super) fn handle_blocked_commands_response(&mut self, command_line: Option<&str>) {
+        self.blocked_commands_review = None;
+        let Some(command_line) = command_line else {
+            return;
+        };
+        if let Some(command) = self
+            .command_gate
+            .held_back()
+            .into_iter()
+            .find(|c| c.command_line == command_line)
+        {
+            self.show_command_trust_popup(command);
+        }
+    }
+}
@@ -0,0 +1,338 @@
+//! Persisting config changes made inside the editor
+//!
+//! Settings UI saves, theme/keymap/locale switches, and plugin config mutations
+//! all write through [`Editor::persist_config`], which uses
+//! [`write_config_file`](crate::config_io::write_config_file) for atomic, backed-up
+//! writes. The editor remembers a stamp of each config file from when it was
+//! loaded or last written; if a file changed on disk since, the write is refused
+//! and a popup asks whether to reload and reapply the change or overwrite the file.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use rust_i18n::t;
+use serde_json::Value;
+
+use super::Editor;
+use crate::config::{Config, ConfigError};
+use crate::config_io::{
+    apply_config_changes, config_changes, ConfigFileStamp, ConfigLayer, ConfigResolver,
+    DirectoryContext, WriteCheck,
+};
+
+/// Popup item data for "Reload and reapply my change"
+const REAPPLY_ACTION: &str = "reapply";
+/// Popup item data for "Overwrite"
+const OVERWRITE_ACTION: &str = "overwrite";
+
+/// What the editor knows about the config files on disk
+#[derive(Debug, Default)]
+pub(super) struct ConfigSync {
+    /// Stamps of the layer files as last loaded or written (None = file absent)
+    stamps: HashMap<PathBuf, Option<ConfigFileStamp>>,
+    /// The in-memory config as of the last load or write, serialized
+    baseline: Value,
+    /// Write refused because of a conflict, waiting on the user's choice
+    pending: Option<PendingConfigWrite>,
+    /// Values written back to a layer file as they are, because the typed
+    /// config can't hold them (see [`Editor::preserve_config_values`])
+    preserved: HashMap<PathBuf, Value>,
+}
+
+/// A config write waiting on the conflict popup
+#[derive(Debug)]
+struct PendingConfigWrite {
+    layer: ConfigLayer,
+    /// Values changed in memory since the last load or write
+    changes: Value,
+    /// Title of the conflict popup, used to recognize it
+    popup_title: String,
+}
+
+impl ConfigSync {
+    /// Snapshot the layer files and the config loaded from them
+    pub(super) fn new(dir_context: &DirectoryContext, working_dir: &Path, config: &Config) -> Self {
+        let mut sync = Self::default();
+        let resolver = ConfigResolver::new(dir_context.clone(), working_dir.to_path_buf());
+        sync.record(&resolver, config);
+        sync
+    }
+
+    fn record(&mut self, resolver: &ConfigResolver, config: &Config) {
+        self.stamps.clear();
+        for layer in [
+            ConfigLayer::User,
+            ConfigLayer::Project,
+            ConfigLayer::Session,
+        ] {
+            let Some(path) = resolver.layer_write_path(layer) else {
+                continue;
+            };
+            // Without a stamp the next write to this file is unchecked
+            match ConfigFileStamp::read(&path) {
+                Ok(stamp) => {
+                    self.stamps.insert(path, stamp);
+                }
+                Err(e) => tracing::warn!("Failed to read {}: {}", path.display(), e),
+            }
+        }
+        self.baseline = serialize_config(config);
+    }
+}
+
+fn serialize_config(config: &Config) -> Value {
+    serde_json::to_value(config).unwrap_or(Value::Null)
+}
+
+impl Editor {
+    fn config_resolver(&self) -> ConfigResolver {
+        ConfigResolver::new(self.dir_context.clone(), self.working_dir.clone())
+    }
+
+    /// Re-snapshot the config files after loading the config from disk
+    pub(super) fn record_config_on_disk(&mut self) {
+        let resolver = self.config_resolver();
+        self.config_sync.record(&resolver, &self.config);
+        // The files may have been edited; preserved values are read again when
+        // the settings UI is next opened
+        self.config_sync.preserved.clear();
+    }
+
+    /// Keep `values` (unrecognized settings, or values stored with another type)
+    /// in a layer file whenever it is written
+    pub(super) fn preserve_config_values(&mut self, layer: ConfigLayer, values: Value) {
+        if let Some(path) = self.config_resolver().layer_write_path(layer) {
+            self.config_sync.preserved.insert(path, values);
+        }
+    }
+
+    /// Write the in-memory config to a layer file.
+    ///
+    /// The write is refused with [`ConfigError::Conflict`] if the file changed on
+    /// disk since it was loaded or last written; the conflict popup is then shown
+    /// so the user can reload and reapply their change, or overwrite the file.
+    pub(crate) fn persist_config(&mut self, layer: ConfigLayer) -> Result<(), ConfigError> {
+        let check = self
+            .config_resolver()
+            .layer_write_path(layer)
+            .and_then(|path| self.config_sync.stamps.get(&path).copied())
+            .map_or(WriteCheck::Overwrite, WriteCheck::Expect);
+
+        let result = self.write_config_layer(layer, check);
+        if let Err(ConfigError::Conflict(path)) = &result {
+            tracing::warn!("Config file {} changed on disk, not overwriting", path);
+            let changes =
+                config_changes(&self.config_sync.baseline, &serialize_config(&self.config));
+            self.show_config_conflict_popup(layer, changes);
+        }
+        result
+    }
+
+    fn write_config_layer(
+        &mut self,
+        layer: ConfigLayer,
+        check: WriteCheck,
+    ) -> Result<(), ConfigError> {
+        let resolver = self.config_resolver();
+        let path = resolver.layer_write_path(layer);
+        let preserved = path
+            .as_ref()
+            .and_then(|path| self.config_sync.preserved.get(path))
+            .unwrap_or(&Value::Null);
+        let stamp = resolver.save_to_layer_preserving(&self.config, layer, check, preserved)?;
+        if let Some(path) = path {
+            self.config_sync.stamps.insert(path, Some(stamp));
+        }
+        self.config_sync.baseline = serialize_config(&self.config);
+        Ok(())
+    }
+
+    /// Replace the in-memory config, applying theme, keybinding and LSP changes
+    pub(super) fn replace_config(&mut self, config: Config) {
+        let old_theme = self.configured_theme_name();
+        let old_transparent_background = self.config.ui.transparent_background;
+        let old_todos = self.config.todos.clone();
+        let old_auto_revert = self.config.files.auto_revert;
+        self.config = config;
+
+        // Apply theme change if needed
+        if old_theme != self.configured_theme_name()
+            || old_transparent_background != self.config.ui.transparent_background
+        {
+            self.theme = self.load_theme(&self.configured_theme_name());
+            tracing::info!("Theme changed to '{}'", self.theme.name);
+            self.theme_changed();
+        }
+
+        // Always reload keybindings (complex types don't implement PartialEq)
+        self.keybindings = crate::input::keybindings::KeybindingResolver::new(&self.config);
+        self.apply_ambiguous_width();
+        self.apply_glyphs();
+        self.apply_clipboard_limits();
+        self.apply_auto_revert_mode(old_auto_revert);
+        self.apply_protected_globs();
+        self.apply_gutter_columns();
+        self.apply_log_limits();
+
+        if old_todos != self.config.todos {
+            self.reset_todo_highlights();
+        }
+
+        // Update LSP configs
+        if let Some(ref mut lsp) = self.lsp {
+            for (language, lsp_config) in &self.config.lsp {
+                lsp.set_language_config(language.clone(), lsp_config.clone());
+            }
+        }
+        self.command_gate
+            .refresh(&self.dir_context, &self.working_dir);
+    }
+
+    /// Set a single config value on behalf of a plugin and persist it to the user config
+    pub(super) fn set_config_value(&mut self, path: &str, value: Value) {
+        let config = match crate::config_io::set_config_value(&self.config, path, value) {
+            Ok(config) => config,
+            Err(e) => {
+                tracing::warn!("Plugin failed to set config value '{}': {}", path, e);
+                self.set_status_message(
+                    t!("config.set_value_failed", key = path, error = e.to_string()).to_string(),
+                );
+                return;
+            }
+        };
+        self.replace_config(config);
+
+        match self.persist_config(ConfigLayer::User) {
+            Ok(()) | Err(ConfigError::Conflict(_)) => {}
+            Err(e) => {
+                self.set_status_message(
+                    t!("error.config_save_failed", error = e.to_string()).to_string(),
+                );
+            }
+        }
+    }
+
+    fn show_config_conflict_popup(&mut self, layer: ConfigLayer, changes: Value) {
+        use crate::model::event::{
+            PopupContentData, PopupData, PopupListItemData, PopupPositionData,
+        };
+
+        let file = self
+            .config_resolver()
+            .layer_write_path(layer)
+            .map(|p| p.display().to_string())
+            .unwrap_or_default();
+        let title = t!("config.conflict_title").to_string();
+
+        let popup = PopupData {
+            title: Some(title.clone()),
+            description: Some(t!("config.conflict_description", path = file).to_string()),
+            transient: false,
+            content: PopupContentData::List {
+                items: vec![
+                    PopupListItemData {
+                        text: t!("config.conflict_reapply").to_string(),
+                        detail: Some(t!("config.conflict_reapply_detail").to_string()),
+                        icon: None,
+                        data: Some(REAPPLY_ACTION.to_string()),
+                    },
+                    PopupListItemData {
+                        text: t!("config.conflict_overwrite").to_string(),
+                        detail: Some(t!("config.conflict_overwrite_detail").to_string()),
+                        icon: None,
+                        data: Some(OVERWRITE_ACTION.to_string()),
+                    },
+                ],
+                selected: 0,
+            },
+            position: PopupPositionData::Centered,
+            width: 60,
+            max_height: 8,
+            bordered: true,
+        };
+
+        self.config_sync.pending = Some(PendingConfigWrite {
+            layer,
+            changes,
+            popup_title: title,
+        });
+        self.show_popup(popup);
+    }
+
+    /// Whether the topmost popup is the config conflict popup
+    pub(super) fn is_config_conflict_popup(&self) -> bool {
+        let Some(pending) = &self.config_sync.pending else {
+            return false;
+        };
+        self.active_state()
+            .popups
+            .top()
+            .and_then(|popup| popup.title.as_ref())
+            .is_some_and(|title| *title == pending.popup_title)
+    }
+
+    /// Handle the user's choice in the config conflict popup.
+    ///
+    /// `None` means the popup was dismissed: the change stays in effect for this
+    /// session but isn't saved.
+    pub(super) fn handle_config_conflict_response(&mut self, action: Option<&str>) {
+        let Some(pending) = self.config_sync.pending.take() else {
+            return;
+        };
+
+        match action {
+            Some(REAPPLY_ACTION) => self.reapply_config_changes(pending),
+            Some(OVERWRITE_ACTION) => match self
+                .write_config_layer(pending.layer, WriteCheck::Overwrite)
+            {
+                Ok(()) => self.set_status_message(t!("config.conflict_overwritten").to_string()),
+                Err(e) => self.set_status_message(
+                    t!("error.config_save_failed", error = e.to_string()).to_string(),
+                ),
+            },
+            _ => self.set_status_message(t!("config.conflict_not_saved").to_string()),
+        }
+    }
+
+    /// Reload the config from disk and apply the pending changes on top of it
+    fn reapply_config_changes(&mut self, pending: PendingConfigWrite) {
+        let resolver = self.config_resolver();
+        let reloaded = resolver.resolve().and_then(|on_disk| {
+            let mut merged = serialize_config(&on_disk);
+            apply_config_changes(&mut merged, &pending.changes);
+            let merged: Config = serde_json::from_value(merged)
+                .map_err(|e| ConfigError::ParseError(e.to_string()))?;
+            Ok((on_disk, merged))
+        });
+        let (on_disk, merged) = match reloaded {
+            Ok(configs) => configs,
+            Err(e) => {
+                self.set_status_message(
+                    t!("error.config_save_failed", error = e.to_string()).to_string(),
+                );
+                return;
+            }
+        };
+
+        self.config_sync.record(&resolver, &on_disk);
+        self.replace_config(merged);
+
+        match self.persist_config(pending.layer) {
+            Ok(()) => self.set_status_message(t!("config.conflict_reapplied").to_string()),
+            // Changed again in the meantime; the popup is shown again
+            Err(ConfigError::Conflict(_)) => {}
+            Err(e) => self.set_status_message(
+                t!("error.config_save_failed", error = e.to_string()).to_string(),
+            ),
+        }
+
+        self.emit_event(
+            "config_changed",
+            serde_json::json!({
+                "path": resolver
+                    .layer_write_path(pending.layer)
+                    .map(|p| p.to_string_lossy().into_owned()),
+            }),
+        );
+    }
+}
@@ -0,0 +1,306 @@
+//! Terminal cursor shape
+//!
+//! The hardware cursor follows the editing state: a style per global editor
+//! mode (`ui.cursor.modes`, e.g. vi-normal and vi-insert), then
+//! `ui.cursor.selection` while any cursor has a selection, then
+//! `ui.cursor.normal` for read-only buffers and `ui.cursor.insert` otherwise.
+//! Unset states fall back to `editor.cursor_style`. A plugin can override all
+//! of this with `setCursorStyle` for modes the config doesn't know about.
+//!
+//! [`Editor::take_terminal_effects`] asks [`Editor::take_cursor_style_change`]
+//! once per event loop iteration and queues `SetCursorStyle` only when the
+//! answer is `Some`, so the same style is never re-emitted and changes are
+//! spaced at least [`MIN_CHANGE_INTERVAL`] apart.
+//!
+//! The shape the terminal had before the editor changed it is recorded with
+//! [`save_startup_shape`], and every exit path restores [`startup_shape`].
+
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::config::{CursorShapeConfig, CursorStyle};
+
+use super::Editor;
+
+/// Minimum time between two cursor style changes
+pub const MIN_CHANGE_INTERVAL: Duration = Duration::from_millis(30);
+
+static STARTUP_SHAPE: Mutex<Option<CursorStyle>> = Mutex::new(None);
+
+/// Record the cursor shape to restore when the editor exits
+pub fn save_startup_shape(style: CursorStyle) {
+    if let Ok(mut shape) = STARTUP_SHAPE.lock() {
+        *shape = Some(style);
+    }
+}
+
+/// Cursor shape recorded at startup, or the terminal default if none was
+///
+/// Also called from the panic hook, so a poisoned lock is not an error.
+pub fn startup_shape() -> CursorStyle {
+    STARTUP_SHAPE
+        .lock()
+        .ok()
+        .and_then(|shape| *shape)
+        .unwrap_or_default()
+}
+
+/// Editing state that decides the cursor style
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CursorState<'a> {
+    /// Style set by a plugin with `setCursorStyle`
+    pub plugin_style: Option<CursorStyle>,
+    /// Global editor mode set with `setEditorMode`
+    pub editor_mode: Option<&'a str>,
+    /// Any cursor of the active buffer has a non-empty selection
+    pub has_selection: bool,
+    /// The active buffer is read-only or has a plugin buffer mode
+    pub read_only: bool,
+}
+
+/// Cursor style for an editing state
+pub fn resolve_cursor_style(
+    config: &CursorShapeConfig,
+    fallback: CursorStyle,
+    state: &CursorState,
+) -> CursorStyle {
+    if let Some(style) = state.plugin_style {
+        return style;
+    }
+    if let Some(style) = state.editor_mode.and_then(|mode| config.modes.get(mode)) {
+        return *style;
+    }
+    let configured = if state.has_selection {
+        config.selection
+    } else if state.read_only {
+        config.normal
+    } else {
+        config.insert
+    };
+    configured.unwrap_or(fallback)
+}
+
+/// Deduplicates and rate-limits cursor style changes
+#[derive(Debug, Default)]
+pub struct CursorShapeTracker {
+    /// Style last written to the terminal
+    emitted: Option<CursorStyle>,
+    /// When it was written
+    last_change: Option<Instant>,
+}
+
+impl CursorShapeTracker {
+    /// Style to write to the terminal now, if any
+    ///
+    /// Returns None when `desired` is already showing or the last change was
+    /// less than [`MIN_CHANGE_INTERVAL`] ago; a skipped change is returned by
+    /// a later call once the interval has passed.
+    pub fn next_change(&mut self, desired: CursorStyle, now: Instant) -> Option<CursorStyle> {
+        if self.emitted == Some(desired) {
+            return None;
+        }
+        if self
+            .last_change
+            .is_some_and(|t| now.duration_since(t) < MIN_CHANGE_INTERVAL)
+        {
+            return None;
+        }
+        self.emitted = Some(desired);
+        self.last_change = Some(now);
+        Some(desired)
+    }
+}
+
+impl Editor {
+    /// Cursor style for the current editing state
+    pub fn desired_cursor_style(&self) -> CursorStyle {
+        let has_selection = self.active_state().cursors.iter().any(|(_, cursor)| {
+            cursor.has_block_selection()
+                || cursor
+                    .selection_range()
+                    .is_some_and(|range| !range.is_empty())
+        });
+        let state = CursorState {
+            plugin_style: self.plugin_cursor_style,
+            editor_mode: self.editor_mode.as_deref(),
+            has_selection,
+            read_only: self.is_active_buffer_read_only() || self.active_buffer_mode().is_some(),
+        };
+        resolve_cursor_style(
+            &self.config.ui.cursor,
+            self.config.editor.cursor_style,
+            &state,
+        )
+    }
+
+    /// Cursor style the event loop should write to the terminal, if it changed
+    pub fn take_cursor_style_change(&mut self, now: Instant) -> Option<CursorStyle> {
+        let desired = self.desired_cursor_style();
+        self.cursor_shape.next_change(desired, now)
+    }
+
+    /// Set or clear the plugin cursor style override
+    pub(super) fn handle_set_cursor_style(&mut self, style: Option<String>) {
+        self.plugin_cursor_style = match style.as_deref() {
+            None => None,
+            Some(name) => match CursorStyle::from_str(name) {
+                Some(style) => Some(style),
+                None => {
+                    tracing::warn!("Unknown cursor style from plugin: {}", name);
+                    return;
+                }
+            },
+        };
+        tracing::debug!("Set plugin cursor style: {:?}", self.plugin_cursor_style);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ms(n: u64) -> Duration {
+        Duration::from_millis(n)
+    }
+
+    fn mode(name: &str) -> CursorState<'_> {
+        CursorState {
+            editor_mode: Some(name),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_resolve_falls_back_to_editor_cursor_style() {
+        let config = CursorShapeConfig::default();
+        let fallback = CursorStyle::BlinkingBlock;
+
+        assert_eq!(
+            resolve_cursor_style(&config, fallback, &CursorState::default()),
+            fallback
+        );
+        let selecting = CursorState {
+            has_selection: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            resolve_cursor_style(&config, fallback, &selecting),
+            fallback
+        );
+        // Modes missing from the map use the plain editing states
+        assert_eq!(
+            resolve_cursor_style(&config, fallback, &mode("vi-visual")),
+            fallback
+        );
+    }
+
+    #[test]
+    fn test_resolve_precedence() {
+        let config = CursorShapeConfig {
+            normal: Some(CursorStyle::SteadyUnderline),
+            insert: Some(CursorStyle::SteadyBar),
+            selection: Some(CursorStyle::BlinkingUnderline),
+            ..Default::default()
+        };
+        let fallback = CursorStyle::Default;
+        let resolve = |state: CursorState| resolve_cursor_style(&config, fallback, &state);
+
+        assert_eq!(resolve(CursorState::default()), CursorStyle::SteadyBar);
+        assert_eq!(
+            resolve(CursorState {
+                read_only: true,
+                ..Default::default()
+            }),
+            CursorStyle::SteadyUnderline
+        );
+        assert_eq!(
+            resolve(CursorState {
+                read_only: true,
+                has_selection: true,
+                ..Default::default()
+            }),
+            CursorStyle::BlinkingUnderline
+        );
+        assert_eq!(
+            resolve(CursorState {
+                has_selection: true,
+                ..mode("vi-normal")
+            }),
+            CursorStyle::SteadyBlock
+        );
+        assert_eq!(
+            resolve(CursorState {
+                plugin_style: Some(CursorStyle::BlinkingBlock),
+                ..mode("vi-normal")
+            }),
+            CursorStyle::BlinkingBlock
+        );
+    }
+
+    #[test]
+    fn test_vi_mode_transitions() {
+        let config = CursorShapeConfig::default();
+        let fallback = CursorStyle::Default;
+        let mut tracker = CursorShapeTracker::default();
+        let start = Instant::now();
+
+        // (time, mode) as the event loop would see them
+        let script = [
+            (0, None),
+            (5, None),
+            (100, Some("vi-normal")),
+            (120, Some("vi-normal")),
+            (200, Some("vi-insert")),
+            (210, Some("vi-normal")),
+            (250, Some("vi-normal")),
+            (300, Some("vi-insert")),
+            (400, None),
+        ];
+        let changes: Vec<(u64, CursorStyle)> = script
+            .iter()
+            .filter_map(|&(at, editor_mode)| {
+                let state = CursorState {
+                    editor_mode,
+                    ..Default::default()
+                };
+                let desired = resolve_cursor_style(&config, fallback, &state);
+                tracker
+                    .next_change(desired, start + ms(at))
+                    .map(|style| (at, style))
+            })
+            .collect();
+
+        assert_eq!(
+            changes,
+            vec![
+                (0, CursorStyle::Default),
+                (100, CursorStyle::SteadyBlock),
+                (200, CursorStyle::BlinkingBar),
+                // The switch at 210 is too soon after 200 and shows at 250
+                (250, CursorStyle::SteadyBlock),
+                (300, CursorStyle::BlinkingBar),
+                (400, CursorStyle::Default),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_tracker_drops_change_reverted_within_interval() {
+        let mut tracker = CursorShapeTracker::default();
+        let start = Instant::now();
+
+        assert_eq!(
+            tracker.next_change(CursorStyle::SteadyBlock, start),
+            Some(CursorStyle::SteadyBlock)
+        );
+        assert_eq!(
+            tracker.next_change(CursorStyle::BlinkingBar, start + ms(5)),
+            None
+        );
+        // Back to the emitted style before the interval passed: nothing to write
+        assert_eq!(
+            tracker.next_change(CursorStyle::SteadyBlock, start + ms(40)),
+            None
+        );
+    }
+}
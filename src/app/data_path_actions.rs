@@ -0,0 +1,58 @@
+//! Key paths in JSON, YAML and TOML buffers
+//!
+//! The breadcrumb, "Copy Key Path", "Go to Key Path" and "Select Key Value"
+//! all read the outline cached on the buffer's state, which is parsed again
+//! only after the buffer changes.
+
+use rust_i18n::t;
+
+use crate::primitives::data_path::{format_path, parse_path};
+
+use super::Editor;
+
+impl Editor {
+    /// Key path at the primary cursor of the active buffer, written in the
+    /// configured style
+    ///
+    /// None unless the buffer is a JSON, YAML or TOML file.
+    pub fn data_path_at_cursor(&mut self) -> Option<String> {
+        let style = self.config.editor.data_path_style;
+        let state = self.active_state_mut();
+        let position = state.cursors.primary().position;
+        let outline = state.data_outline.get(&state.buffer)?;
+        Some(format_path(&outline.path_at(position), style))
+    }
+
+    /// Copy the key path at the cursor to the clipboard
+    pub(super) fn copy_data_path(&mut self) {
+        match self.data_path_at_cursor() {
+            Some(path) if !path.is_empty() && path != "." => {
+                self.clipboard.copy(path.clone());
+                self.set_status_message(t!("data_path.copied", path = path).to_string());
+            }
+            Some(_) => self.set_status_message(t!("data_path.at_root").to_string()),
+            None => self.set_status_message(t!("data_path.not_data_file").to_string()),
+        }
+    }
+
+    /// Answer to the "Go to key path" prompt
+    pub(super) fn handle_goto_data_path_input(&mut self, input: &str) {
+        let Some(path) = parse_path(input) else {
+            self.set_status_message(t!("data_path.invalid", input = input).to_string());
+            return;
+        };
+        let state = self.active_state_mut();
+        let Some(outline) = state.data_outline.get(&state.buffer) else {
+            self.set_status_message(t!("data_path.not_data_file").to_string());
+            return;
+        };
+        match outline.find(&path) {
+            Some(offset) => {
+                self.goto_byte(offset);
+            }
+            None => {
+                self.set_status_message(t!("data_path.not_found", path = input.trim()).to_string())
+            }
+        }
+    }
+}
@@ -0,0 +1,440 @@
+//! "Compare Directories…": the differences between two directory trees
+//!
+//! The trees are compared on the worker pool (see
+//! [`crate::services::dir_compare`]) and shown in a read-only virtual buffer,
+//! grouped by directory, one line per file with a glyph for its status:
+//! `~` different, `-` only on the left, `+` only on the right and `=`
+//! identical. Enter on a file that differs opens both sides in the buffer
+//! comparison ([`Editor::compare_buffers`]); on any other file it opens the
+//! one there is. `i` hides or shows the identical files and `g` compares the
+//! trees again in place. A comparison that runs past
+//! [`PROGRESS_AFTER_FILES`] files shows its progress.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use rust_i18n::t;
+use serde_json::json;
+
+use super::Editor;
+use crate::model::event::BufferId;
+use crate::primitives::text_property::TextPropertyEntry;
+use crate::services::dir_compare::{self, DirCompareRequest, DirComparison, FileStatus};
+use crate::services::worker_pool::JobHandle;
+use crate::view::prompt::PromptType;
+
+/// Display name of the comparison buffer
+pub(crate) const DIR_COMPARE_BUFFER_NAME: &str = "*Compare Directories*";
+
+/// Buffer mode of the comparison buffer (bindings live in `ModeRegistry::new`)
+pub(crate) const DIR_COMPARE_MODE: &str = "dir-compare";
+
+/// Files compared before a running comparison shows its progress
+const PROGRESS_AFTER_FILES: usize = 1_000;
+
+/// The open directory comparison
+pub(crate) struct DirComparePanel {
+    buffer_id: BufferId,
+    left: PathBuf,
+    right: PathBuf,
+    /// Result of the last finished comparison
+    comparison: Option<DirComparison>,
+    /// Why the last comparison failed
+    error: Option<String>,
+    hide_identical: bool,
+    /// Comparison in progress
+    scan: Option<DirCompareScan>,
+}
+
+/// A comparison running on the worker pool
+struct DirCompareScan {
+    compare_id: u64,
+    job: JobHandle,
+    /// Files compared so far
+    compared: Arc<AtomicUsize>,
+    /// Count last shown
+    shown: usize,
+    progress_id: Option<u32>,
+}
+
+/// Glyph of a file status in the comparison buffer
+fn status_glyph(status: FileStatus) -> char {
+    match status {
+        FileStatus::Different => '~',
+        FileStatus::OnlyLeft => '-',
+        FileStatus::OnlyRight => '+',
+        FileStatus::Identical => '=',
+    }
+}
+
+impl DirComparePanel {
+    /// Build the comparison buffer content
+    fn entries(&self) -> Vec<TextPropertyEntry> {
+        let mut entries = vec![TextPropertyEntry::text(format!(
+            "{}\n",
+            t!(
+                "dir_compare.title",
+                left = self.left.display().to_string(),
+                right = self.right.display().to_string()
+            )
+        ))
+        .with_property("type", json!("header"))];
+
+        let mut counts = match &self.comparison {
+            Some(comparison) => t!(
+                "dir_compare.counts",
+                different = comparison.count(FileStatus::Different),
+                only_left = comparison.count(FileStatus::OnlyLeft),
+                only_right = comparison.count(FileStatus::OnlyRight),
+                identical = comparison.count(FileStatus::Identical)
+            )
+            .to_string(),
+            None => String::new(),
+        };
+        if self.scan.is_some() {
+            counts.push_str(&format!("  ({})", t!("dir_compare.comparing")));
+        }
+        if self.hide_identical {
+            counts.push_str(&format!("  {}", t!("dir_compare.identical_hidden")));
+        }
+        entries.push(
+            TextPropertyEntry::text(format!("{}\n", counts.trim_start()))
+                .with_property("type", json!("header")),
+        );
+        entries.push(
+            TextPropertyEntry::text(format!("{}\n\n", t!("dir_compare.help")))
+                .with_property("type", json!("help")),
+        );
+
+        if let Some(error) = &self.error {
+            entries.push(TextPropertyEntry::text(format!(
+                "  {}\n",
+                t!("dir_compare.failed", error = error)
+            )));
+            return entries;
+        }
+        let Some(comparison) = &self.comparison else {
+            return entries;
+        };
+
+        // Files grouped by their directory, the top directory first
+        let mut directories: BTreeMap<&Path, Vec<_>> = BTreeMap::new();
+        for file in &comparison.files {
+            if self.hide_identical && file.status == FileStatus::Identical {
+                continue;
+            }
+            let directory = file.path.parent().unwrap_or(Path::new(""));
+            directories.entry(directory).or_default().push(file);
+        }
+        if directories.is_empty() {
+            let message = if comparison.files.is_empty() {
+                t!("dir_compare.empty")
+            } else {
+                t!("dir_compare.no_differences")
+            };
+            entries.push(TextPropertyEntry::text(format!("  {}\n", message)));
+        }
+
+        for (directory, files) in directories {
+            let name = if directory.as_os_str().is_empty() {
+                ".".to_string()
+            } else {
+                directory.to_string_lossy().replace('\\', "/")
+            };
+            entries.push(
+                TextPropertyEntry::text(format!("{}/ ({})\n", name, files.len()))
+                    .with_property("type", json!("directory")),
+            );
+            for file in files {
+                let name = file.path.file_name().unwrap_or_default().to_string_lossy();
+                entries.push(
+                    TextPropertyEntry::text(format!("  {} {}\n", status_glyph(file.status), name))
+                        .with_property("type", json!("file"))
+                        .with_property("path", json!(file.path.to_string_lossy()))
+                        .with_property("status", json!(file.status.as_str())),
+                );
+            }
+        }
+
+        entries
+    }
+}
+
+impl Editor {
+    /// Prompt for the two directories to compare
+    pub fn start_compare_directories_prompt(&mut self) {
+        self.start_prompt(
+            t!("dir_compare.prompt_left").to_string(),
+            PromptType::CompareDirectoriesLeft,
+        );
+    }
+
+    /// Take the left directory and prompt for the right one
+    pub(super) fn handle_compare_directories_left(&mut self, input: &str) {
+        if let Some(left) = self.resolve_project_dir(Path::new(input.trim())) {
+            self.start_prompt(
+                t!(
+                    "dir_compare.prompt_right",
+                    left = left.display().to_string()
+                )
+                .to_string(),
+                PromptType::CompareDirectoriesRight { left },
+            );
+        }
+    }
+
+    /// Compare the left directory with the one typed
+    pub(super) fn handle_compare_directories_right(&mut self, left: PathBuf, input: &str) {
+        if let Some(right) = self.resolve_project_dir(Path::new(input.trim())) {
+            self.compare_directories(left, right);
+        }
+    }
+
+    /// Show the differences between the trees under `left` and `right`,
+    /// reusing the comparison buffer if it is open
+    pub fn compare_directories(&mut self, left: PathBuf, right: PathBuf) {
+        if let Some(panel) = self.dir_compare_panel.as_mut() {
+            if panel.left != left || panel.right != right {
+                panel.comparison = None;
+            }
+            panel.left = left;
+            panel.right = right;
+            let buffer_id = panel.buffer_id;
+            self.set_active_buffer(buffer_id);
+            self.start_dir_compare();
+            return;
+        }
+
+        let buffer_id = self.create_virtual_buffer(
+            DIR_COMPARE_BUFFER_NAME.to_string(),
+            DIR_COMPARE_MODE.to_string(),
+            true,
+        );
+        if let Some(state) = self.buffers.get_mut(&buffer_id) {
+            state.editing_disabled = true;
+            state.margins.set_line_numbers(false);
+        }
+        self.dir_compare_panel = Some(DirComparePanel {
+            buffer_id,
+            left,
+            right,
+            comparison: None,
+            error: None,
+            hide_identical: false,
+            scan: None,
+        });
+        self.set_active_buffer(buffer_id);
+        self.start_dir_compare();
+    }
+
+    /// Compare the trees again, keeping the result shown until it's done
+    pub fn refresh_dir_compare(&mut self) {
+        if self.dir_compare_panel.is_some() {
+            self.start_dir_compare();
+        } else {
+            self.start_compare_directories_prompt();
+        }
+    }
+
+    /// Hide or show the identical files
+    pub fn toggle_dir_compare_identical(&mut self) {
+        let Some(panel) = self.dir_compare_panel.as_mut() else {
+            return;
+        };
+        panel.hide_identical = !panel.hide_identical;
+        self.render_dir_compare_panel();
+    }
+
+    /// The last finished directory comparison, if one is open
+    pub fn dir_comparison(&self) -> Option<&DirComparison> {
+        self.dir_compare_panel.as_ref()?.comparison.as_ref()
+    }
+
+    /// Whether a directory comparison is running
+    pub fn is_comparing_directories(&self) -> bool {
+        self.dir_compare_panel
+            .as_ref()
+            .is_some_and(|panel| panel.scan.is_some())
+    }
+
+    /// Start a comparison, replacing any in progress
+    fn start_dir_compare(&mut self) {
+        static NEXT_COMPARE_ID: AtomicU64 = AtomicU64::new(1);
+
+        let Some(panel) = self.dir_compare_panel.as_mut() else {
+            return;
+        };
+        let compared = Arc::new(AtomicUsize::new(0));
+        let request = DirCompareRequest {
+            compare_id: NEXT_COMPARE_ID.fetch_add(1, Ordering::Relaxed),
+            left: panel.left.clone(),
+            right: panel.right.clone(),
+            compared: Arc::clone(&compared),
+        };
+        let compare_id = request.compare_id;
+        let job = dir_compare::start_compare(&self.worker_pool, request);
+        panel.error = None;
+        let previous = panel.scan.replace(DirCompareScan {
+            compare_id,
+            job,
+            compared,
+            shown: 0,
+            progress_id: None,
+        });
+        if let Some(previous) = previous {
+            self.stop_dir_compare_scan(previous);
+        }
+        self.render_dir_compare_panel();
+        self.set_status_message(t!("dir_compare.comparing").to_string());
+    }
+
+    /// Cancel a comparison and finish its progress
+    fn stop_dir_compare_scan(&mut self, scan: DirCompareScan) {
+        scan.job.cancel();
+        if let Some(progress_id) = scan.progress_id {
+            self.finish_progress(progress_id);
+        }
+    }
+
+    /// Show the progress of a long comparison
+    ///
+    /// Returns true if anything visible changed.
+    pub(super) fn poll_dir_compare_progress(&mut self) -> bool {
+        let Some(scan) = self
+            .dir_compare_panel
+            .as_mut()
+            .and_then(|panel| panel.scan.as_mut())
+        else {
+            return false;
+        };
+        let compared = scan.compared.load(Ordering::Relaxed);
+        if compared == scan.shown || compared < PROGRESS_AFTER_FILES {
+            return false;
+        }
+        scan.shown = compared;
+        let progress_id = scan.progress_id;
+        let progress_id = match progress_id {
+            Some(progress_id) => progress_id,
+            None => {
+                let progress_id = self.start_progress(t!("dir_compare.progress_title").to_string());
+                if let Some(scan) = self
+                    .dir_compare_panel
+                    .as_mut()
+                    .and_then(|panel| panel.scan.as_mut())
+                {
+                    scan.progress_id = Some(progress_id);
+                }
+                progress_id
+            }
+        };
+        self.update_progress(
+            progress_id,
+            None,
+            Some(t!("dir_compare.progress", count = compared).to_string()),
+        );
+        true
+    }
+
+    /// Show the result of a finished comparison
+    pub(super) fn handle_dir_compare_finished(
+        &mut self,
+        compare_id: u64,
+        result: Result<DirComparison, String>,
+    ) {
+        let Some(panel) = self.dir_compare_panel.as_mut() else {
+            return;
+        };
+        if panel.scan.as_ref().map(|scan| scan.compare_id) != Some(compare_id) {
+            return;
+        }
+        let scan = panel.scan.take();
+        let message = match result {
+            Ok(comparison) => {
+                let message = t!(
+                    "dir_compare.finished",
+                    different = comparison.count(FileStatus::Different),
+                    only_left = comparison.count(FileStatus::OnlyLeft),
+                    only_right = comparison.count(FileStatus::OnlyRight),
+                    identical = comparison.count(FileStatus::Identical)
+                )
+                .to_string();
+                panel.comparison = Some(comparison);
+                message
+            }
+            Err(error) => {
+                let message = t!("dir_compare.failed", error = &error).to_string();
+                panel.comparison = None;
+                panel.error = Some(error);
+                message
+            }
+        };
+        if let Some(scan) = scan {
+            self.stop_dir_compare_scan(scan);
+        }
+        self.render_dir_compare_panel();
+        self.set_status_message(message);
+    }
+
+    /// Re-render the comparison buffer from the panel state
+    fn render_dir_compare_panel(&mut self) {
+        let Some(panel) = &self.dir_compare_panel else {
+            return;
+        };
+        let buffer_id = panel.buffer_id;
+        let entries = panel.entries();
+        if let Err(e) = self.set_virtual_buffer_content(buffer_id, entries) {
+            tracing::error!("Failed to render directory comparison: {}", e);
+        }
+    }
+
+    /// Open the file under the cursor, in the buffer comparison if it
+    /// differs between the trees
+    pub(super) fn dir_compare_open(&mut self) {
+        let Some(panel) = &self.dir_compare_panel else {
+            return;
+        };
+        if self.active_buffer() != panel.buffer_id {
+            return;
+        }
+        let (left_root, right_root) = (panel.left.clone(), panel.right.clone());
+        let target = self.get_text_properties_at_cursor().and_then(|properties| {
+            properties.into_iter().find_map(|p| {
+                let path = PathBuf::from(p.get("path")?.as_str()?);
+                let status = FileStatus::from_name(p.get("status")?.as_str()?)?;
+                Some((path, status))
+            })
+        });
+        let Some((path, status)) = target else {
+            return;
+        };
+        let (left, right) = (left_root.join(&path), right_root.join(&path));
+
+        let opened = match status {
+            FileStatus::Different => self.open_file(&left).and_then(|left_id| {
+                let right_id = self.open_file_no_focus(&right)?;
+                self.compare_buffers(left_id, right_id);
+                Ok(())
+            }),
+            FileStatus::OnlyRight => self.open_file(&right).map(|_| ()),
+            FileStatus::OnlyLeft | FileStatus::Identical => self.open_file(&left).map(|_| ()),
+        };
+        if let Err(e) = opened {
+            self.set_status_message(t!("file.error_opening", error = e.to_string()).to_string());
+        }
+    }
+
+    /// Forget the comparison when its buffer is closed
+    pub(super) fn on_dir_compare_buffer_closed(&mut self, buffer_id: BufferId) {
+        if self
+            .dir_compare_panel
+            .as_ref()
+            .is_some_and(|panel| panel.buffer_id == buffer_id)
+        {
+            if let Some(scan) = self.dir_compare_panel.take().and_then(|panel| panel.scan) {
+                self.stop_dir_compare_scan(scan);
+            }
+        }
+    }
+}
@@ -0,0 +1,241 @@
+//! Distraction-free writing mode
+//!
+//! "Toggle Distraction-Free Mode" hides the menu bar, status bar, tab strips,
+//! gutters and file explorer, collapses the split layout to the active split
+//! (by maximizing it, so the other splits and panels are kept) and centers
+//! the text in a wrapped column of `distraction_free.width` characters, using
+//! the split's compose width.
+//!
+//! Everything changed on entry is recorded in [`DistractionFreeState`] and put
+//! back on exit. Sessions are saved with the recorded layout, so a restart
+//! comes back in the normal layout unless
+//! `distraction_free.restore_with_session` is set.
+
+use std::collections::HashMap;
+use std::ops::Range;
+
+use ratatui::layout::Rect;
+use ratatui::Frame;
+use rust_i18n::t;
+
+use super::Editor;
+use crate::input::keybindings::KeyContext;
+use crate::model::buffer::TextBuffer;
+use crate::model::event::{BufferId, SplitId};
+
+/// The layout as it was before entering distraction-free mode
+pub(crate) struct DistractionFreeState {
+    pub menu_bar_visible: bool,
+    pub file_explorer_visible: bool,
+    /// Whether entering the mode maximized the split (false with one split)
+    pub maximized_split: bool,
+    /// The split whose text is centered
+    pub split_id: SplitId,
+    pub compose_width: Option<u16>,
+    pub line_wrap: bool,
+    /// Line number visibility of each buffer open at the time
+    pub line_numbers: HashMap<BufferId, bool>,
+}
+
+impl Editor {
+    /// Whether distraction-free mode is on
+    pub fn is_distraction_free(&self) -> bool {
+        self.distraction_free.is_some()
+    }
+
+    /// Enter or leave distraction-free mode
+    pub fn toggle_distraction_free(&mut self) {
+        if self.distraction_free.is_some() {
+            self.exit_distraction_free();
+            self.set_status_message(t!("toggle.distraction_free_off").to_string());
+        } else {
+            self.enter_distraction_free();
+            self.set_status_message(t!("toggle.distraction_free_on").to_string());
+        }
+    }
+
+    pub(super) fn enter_distraction_free(&mut self) {
+        if self.distraction_free.is_some() {
+            return;
+        }
+        let split_id = self.split_manager.active_split();
+        let maximized_split =
+            !self.split_manager.is_maximized() && self.split_manager.maximize_split().is_ok();
+
+        let view_state = self.split_view_states.get_mut(&split_id);
+        let (compose_width, line_wrap) = match view_state {
+            Some(vs) => {
+                let saved = (vs.compose_width, vs.viewport.line_wrap_enabled);
+                vs.compose_width = Some(self.config.distraction_free.width.max(1));
+                vs.viewport.line_wrap_enabled = true;
+                saved
+            }
+            None => (None, self.config.editor.line_wrap),
+        };
+
+        let mut line_numbers = HashMap::new();
+        for (buffer_id, state) in self.buffers.iter_mut() {
+            line_numbers.insert(*buffer_id, state.margins.show_line_numbers);
+            state.margins.set_line_numbers(false);
+        }
+
+        self.distraction_free = Some(DistractionFreeState {
+            menu_bar_visible: self.menu_bar_visible,
+            file_explorer_visible: self.file_explorer_visible,
+            maximized_split,
+            split_id,
+            compose_width,
+            line_wrap,
+            line_numbers,
+        });
+
+        self.menu_bar_visible = false;
+        self.menu_state.close_menu();
+        self.file_explorer_visible = false;
+        if self.key_context == KeyContext::FileExplorer {
+            self.key_context = KeyContext::Normal;
+        }
+        self.resize_visible_terminals();
+    }
+
+    pub(super) fn exit_distraction_free(&mut self) {
+        let Some(saved) = self.distraction_free.take() else {
+            return;
+        };
+
+        self.menu_bar_visible = saved.menu_bar_visible;
+        self.file_explorer_visible = saved.file_explorer_visible;
+        if self.file_explorer_visible && self.file_explorer.is_none() {
+            self.init_file_explorer();
+        }
+
+        if saved.maximized_split && self.split_manager.is_maximized() {
+            let _ = self.split_manager.unmaximize_split();
+        }
+
+        if let Some(vs) = self.split_view_states.get_mut(&saved.split_id) {
+            vs.compose_width = saved.compose_width;
+            vs.viewport.line_wrap_enabled = saved.line_wrap;
+        }
+
+        for (buffer_id, show) in saved.line_numbers {
+            if let Some(state) = self.buffers.get_mut(&buffer_id) {
+                state.margins.set_line_numbers(show);
+            }
+        }
+        self.resize_visible_terminals();
+    }
+
+    /// Dim the lines of the active split outside the paragraph with the cursor
+    ///
+    /// Only runs in distraction-free mode with `dim_other_paragraphs` set.
+    pub(super) fn render_distraction_free_dimming(&mut self, frame: &mut Frame) {
+        if self.distraction_free.is_none() || !self.config.distraction_free.dim_other_paragraphs {
+            return;
+        }
+        let active_split = self.split_manager.active_split();
+        let Some(&(_, buffer_id, content_rect, ..)) = self
+            .cached_layout
+            .split_areas
+            .iter()
+            .find(|(split_id, ..)| *split_id == active_split)
+        else {
+            return;
+        };
+        let Some(mappings) = self.cached_layout.view_line_mappings.get(&active_split) else {
+            return;
+        };
+        let estimated_line_length = self.config.editor.estimated_line_length;
+        let Some(state) = self.buffers.get_mut(&buffer_id) else {
+            return;
+        };
+        let cursor = state.cursors.primary().position;
+        let paragraph = paragraph_range(&mut state.buffer, cursor, estimated_line_length);
+
+        for row in 0..content_rect.height {
+            let in_paragraph = mappings.get(row as usize).is_some_and(|mapping| {
+                let byte = mapping
+                    .char_source_bytes
+                    .iter()
+                    .flatten()
+                    .next()
+                    .copied()
+                    .unwrap_or(mapping.line_end_byte);
+                paragraph.start <= byte && byte <= paragraph.end
+            });
+            if !in_paragraph {
+                let area = Rect::new(content_rect.x, content_rect.y + row, content_rect.width, 1);
+                crate::view::dimming::apply_dimming(frame, area);
+            }
+        }
+    }
+}
+
+/// Bytes of the paragraph at `position`, without its final line ending
+///
+/// A paragraph is the run of non-blank lines around the position; on a blank
+/// line it is just that line.
+fn paragraph_range(
+    buffer: &mut TextBuffer,
+    position: usize,
+    estimated_line_length: usize,
+) -> Range<usize> {
+    fn is_blank(line: &str) -> bool {
+        line.trim().is_empty()
+    }
+    fn content_end(line_start: usize, line: &str) -> usize {
+        line_start + line.trim_end_matches(['\n', '\r']).len()
+    }
+
+    let mut iter = buffer.line_iterator(position, estimated_line_length);
+    let Some((line_start, line)) = iter.next() else {
+        return position..position;
+    };
+    let mut end = content_end(line_start, &line);
+    if is_blank(&line) {
+        return line_start..end;
+    }
+    while let Some((next_start, next)) = iter.next() {
+        if is_blank(&next) {
+            break;
+        }
+        end = content_end(next_start, &next);
+    }
+
+    let mut start = line_start;
+    let mut iter = buffer.line_iterator(line_start, estimated_line_length);
+    while let Some((prev_start, prev)) = iter.prev() {
+        if is_blank(&prev) {
+            break;
+        }
+        start = prev_start;
+    }
+    start..end
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn paragraph_text(text: &str, position: usize) -> String {
+        let mut buffer = TextBuffer::from_bytes(text.as_bytes().to_vec());
+        let range = paragraph_range(&mut buffer, position, 80);
+        text[range].to_string()
+    }
+
+    #[test]
+    fn test_paragraph_range_stops_at_blank_lines() {
+        let text = "title\n\nfirst line\nsecond line\n\nlast\n";
+        let second = text.find("second").unwrap();
+        assert_eq!(paragraph_text(text, second), "first line\nsecond line");
+        assert_eq!(paragraph_text(text, 0), "title");
+        assert_eq!(paragraph_text(text, text.find("last").unwrap()), "last");
+    }
+
+    #[test]
+    fn test_paragraph_range_on_blank_line_is_that_line() {
+        let text = "a\n\nb\n";
+        assert_eq!(paragraph_text(text, 2), "");
+        assert_eq!(paragraph_text(text, text.len()), "");
+    }
+}
@@ -0,0 +1,54 @@
+//! Dry runs of commands
+//!
+//! Confirming a command in the palette with Ctrl+Enter runs it as a dry run:
+//! `Editor::dry_run` is set while the action is handled, and commands that
+//! support it report what they would do instead of doing it. Built-in
+//! commands declare support in [`Action::supports_dry_run`]; plugin commands
+//! when they are registered, and their actions get `dry_run` in the options
+//! they are called with. Commands without support simply run.
+
+use rust_i18n::t;
+
+use super::Editor;
+use crate::input::keybindings::Action;
+use crate::view::notifications::{next_notification_id, Notification, NotificationSeverity};
+
+impl Editor {
+    /// Handle `action` as a dry run if it supports one, or normally if not
+    pub(super) fn handle_action_dry_run(&mut self, action: Action) -> std::io::Result<()> {
+        let supported = self
+            .command_registry
+            .read()
+            .unwrap()
+            .supports_dry_run(&action);
+        if !supported {
+            return self.handle_action(action);
+        }
+        self.dry_run = true;
+        let result = self.handle_action(action);
+        self.dry_run = false;
+        result
+    }
+
+    /// Whether the action being handled is a dry run
+    pub fn is_dry_run(&self) -> bool {
+        self.dry_run
+    }
+
+    /// Report what a dry run would have done: `summary`, then one line per
+    /// item it would have touched
+    ///
+    /// The report stays until dismissed, and the notification history keeps
+    /// the whole list.
+    pub(super) fn report_dry_run(&mut self, summary: String, items: Vec<String>) {
+        let mut message = t!("dry_run.report", summary = summary).to_string();
+        for item in items {
+            message.push_str("\n  ");
+            message.push_str(&item);
+        }
+        self.post_notification(
+            Notification::new(next_notification_id(), NotificationSeverity::Info, message)
+                .with_timeout(None),
+        );
+    }
+}
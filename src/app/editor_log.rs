@@ -0,0 +1,553 @@
+//! The editor's own log, viewed inside the editor
+//!
+//! "Open Editor Log" shows the end of the log file written by
+//! [`RotatingLog`] in a read-only buffer, each line colored by the level of
+//! its event. The buffer is read again as the log grows and stays at its end
+//! like a follow-tail buffer until the user moves up. "Filter Editor Log by
+//! Level" hides the less severe lines, and "Open Editor Log at This Entry"
+//! jumps from a line of the warning log to the same event in the full log.
+//!
+//! The log rotates at the size set by `log.max_size_mb`, keeping
+//! `log.max_files` older copies next to it.
+
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+use std::str::FromStr;
+use std::time::Instant;
+
+use rust_i18n::t;
+use tracing::Level;
+
+use super::path_watches::PATH_WATCH_POLL_INTERVAL;
+use super::Editor;
+use crate::input::commands::Suggestion;
+use crate::model::event::BufferId;
+use crate::primitives::ansi::AnsiStripper;
+use crate::primitives::text_property::TextPropertyEntry;
+use crate::services::rotating_log::{LogLimits, RotatingLog, MEGABYTE};
+use crate::view::overlay::{Overlay, OverlayFace, OverlayNamespace};
+use crate::view::prompt::PromptType;
+use crate::view::theme::{DecorationColor, ThemedStyle};
+
+/// Name of the log buffer
+pub const EDITOR_LOG_BUFFER_NAME: &str = "*Editor Log*";
+/// Overlay namespace of the level colors
+const LEVEL_NAMESPACE: &str = "editor-log";
+/// Most of the file read when the log opens or falls far behind
+const MAX_READ_BYTES: u64 = 2 * MEGABYTE;
+/// Most lines kept in the buffer
+const MAX_LINES: usize = 10_000;
+/// Levels offered by the filter prompt, most severe first
+const LEVELS: [Level; 5] = [
+    Level::ERROR,
+    Level::WARN,
+    Level::INFO,
+    Level::DEBUG,
+    Level::TRACE,
+];
+
+/// A line of the log and the level of the event it belongs to
+#[derive(Debug, Clone, PartialEq)]
+struct LogLine {
+    level: Level,
+    text: String,
+}
+
+/// Text appended to the log file since it was last read
+#[derive(Debug)]
+struct LogChunk {
+    text: String,
+    /// Length of the file when it was read
+    end: u64,
+    /// Whether the start of the new text was skipped for being too long
+    skipped: bool,
+}
+
+/// The log buffer and how much of the file it shows
+#[derive(Debug)]
+struct LogView {
+    buffer_id: BufferId,
+    /// Bytes of the log file read so far
+    read_to: u64,
+    /// Start of a line still being written
+    partial: String,
+    lines: VecDeque<LogLine>,
+}
+
+impl LogView {
+    fn new(buffer_id: BufferId) -> Self {
+        Self {
+            buffer_id,
+            read_to: 0,
+            partial: String::new(),
+            lines: VecDeque::new(),
+        }
+    }
+
+    /// Add the complete lines of `chunk`
+    ///
+    /// Returns true if any line was added.
+    fn append(&mut self, chunk: LogChunk) -> bool {
+        let mut new_text = chunk.text;
+        if chunk.skipped {
+            // The read started in the middle of a line
+            self.lines.clear();
+            self.partial.clear();
+            new_text = match new_text.find('\n') {
+                Some(i) => new_text.split_off(i + 1),
+                None => String::new(),
+            };
+        }
+        self.read_to = chunk.end;
+
+        let mut text = std::mem::take(&mut self.partial);
+        text.push_str(&new_text);
+        match text.rfind('\n') {
+            Some(i) => self.partial = text.split_off(i + 1),
+            None => {
+                self.partial = text;
+                return false;
+            }
+        }
+
+        let mut level = self.lines.back().map_or(Level::INFO, |line| line.level);
+        for raw in text.lines() {
+            let (text, _) = AnsiStripper::new().push(raw);
+            // Lines of a multi-line message belong to the event above them
+            level = line_level(&text).unwrap_or(level);
+            self.lines.push_back(LogLine { level, text });
+        }
+        while self.lines.len() > MAX_LINES {
+            self.lines.pop_front();
+        }
+        true
+    }
+}
+
+/// The log file and its viewer
+pub(super) struct EditorLogState {
+    log: Option<RotatingLog>,
+    view: Option<LogView>,
+    /// Least severe level shown
+    min_level: Level,
+    last_poll: Instant,
+}
+
+impl EditorLogState {
+    pub(super) fn new(now: Instant) -> Self {
+        Self {
+            log: None,
+            view: None,
+            min_level: Level::TRACE,
+            last_poll: now,
+        }
+    }
+
+    /// Whether the editor writes a log file
+    pub(super) fn has_log(&self) -> bool {
+        self.log.is_some()
+    }
+}
+
+/// The level named among the first words of a log line
+///
+/// Both the log file ("<time>  WARN target: message") and the warning log
+/// ("<date> <time> WARN target: message") put it there.
+fn line_level(line: &str) -> Option<Level> {
+    line.split_whitespace()
+        .take(3)
+        .filter(|word| word.chars().all(|c| c.is_ascii_alphabetic()))
+        .find_map(|word| Level::from_str(word).ok())
+}
+
+/// The level and "target: message" text of a warning log line
+fn warning_entry(line: &str) -> Option<(Level, &str)> {
+    let mut parts = line.splitn(4, ' ');
+    let (_date, _time) = (parts.next()?, parts.next()?);
+    let level = Level::from_str(parts.next()?).ok()?;
+    let mut text = parts.next()?.trim_end();
+    // Drop the count the warning log adds to repeated messages
+    if let Some(start) = text.rfind(" (suppressed ") {
+        if text.ends_with(" similar messages)") {
+            text = &text[..start];
+        }
+    }
+    (!text.is_empty()).then_some((level, text))
+}
+
+/// Read what was appended to the log at `path` after `from` bytes
+fn read_appended(path: &Path, from: u64) -> std::io::Result<LogChunk> {
+    let mut file = File::open(path)?;
+    let end = file.metadata()?.len();
+    // A shorter file is a new one started by rotation
+    let from = if end < from { 0 } else { from };
+    let start = from.max(end.saturating_sub(MAX_READ_BYTES));
+    file.seek(SeekFrom::Start(start))?;
+    let mut bytes = Vec::new();
+    file.take(end - start).read_to_end(&mut bytes)?;
+    Ok(LogChunk {
+        text: String::from_utf8_lossy(&bytes).into_owned(),
+        end,
+        skipped: start > from,
+    })
+}
+
+/// Theme color of the lines of a level
+fn level_color(level: Level) -> Option<&'static str> {
+    match level {
+        Level::ERROR => Some("diagnostic.error_fg"),
+        Level::WARN => Some("diagnostic.warning_fg"),
+        Level::INFO => Some("diagnostic.info_fg"),
+        Level::DEBUG => Some("diagnostic.hint_fg"),
+        _ => None,
+    }
+}
+
+impl Editor {
+    /// Use `log` as the editor's log file, for the log viewer and the
+    /// `log` config section
+    pub fn set_log_file(&mut self, log: RotatingLog) {
+        self.editor_log.log = Some(log);
+        self.apply_log_limits();
+    }
+
+    /// Give the log file the size limits of the config
+    pub(super) fn apply_log_limits(&self) {
+        if let Some(log) = &self.editor_log.log {
+            log.set_limits(LogLimits {
+                max_size: self.config.log.max_size_mb.saturating_mul(MEGABYTE),
+                max_files: self.config.log.max_files,
+            });
+        }
+    }
+
+    /// Whether `buffer_id` is the log buffer
+    pub(super) fn is_editor_log_buffer(&self, buffer_id: BufferId) -> bool {
+        self.editor_log
+            .view
+            .as_ref()
+            .is_some_and(|view| view.buffer_id == buffer_id)
+    }
+
+    /// Show the end of the log file in a read-only buffer that follows it
+    pub fn open_editor_log(&mut self) {
+        let Some(log) = &self.editor_log.log else {
+            self.set_status_message(t!("editor_log.unavailable").to_string());
+            return;
+        };
+        log.flush();
+
+        let existing = self
+            .editor_log
+            .view
+            .as_ref()
+            .map(|view| view.buffer_id)
+            .filter(|id| self.buffers.contains_key(id));
+        let buffer_id = existing.unwrap_or_else(|| {
+            let buffer_id = self.create_virtual_buffer(
+                EDITOR_LOG_BUFFER_NAME.to_string(),
+                "special".to_string(),
+                true,
+            );
+            if let Some(state) = self.buffers.get_mut(&buffer_id) {
+                state.editing_disabled = true;
+                state.margins.set_line_numbers(false);
+            }
+            self.editor_log.view = Some(LogView::new(buffer_id));
+            buffer_id
+        });
+
+        self.read_editor_log();
+        if let Some(metadata) = self.buffer_metadata.get_mut(&buffer_id) {
+            metadata.follow_tail = true;
+        }
+        self.set_active_buffer(buffer_id);
+        self.refresh_editor_log_buffer();
+    }
+
+    /// Show what was added to the log file since it was last read
+    ///
+    /// Returns true if the log buffer changed.
+    pub(super) fn poll_editor_log(&mut self) -> bool {
+        let Some(view) = &self.editor_log.view else {
+            return false;
+        };
+        if !self.buffers.contains_key(&view.buffer_id) {
+            self.editor_log.view = None;
+            return false;
+        }
+        if self.time_source.elapsed_since(self.editor_log.last_poll) < PATH_WATCH_POLL_INTERVAL {
+            return false;
+        }
+        self.editor_log.last_poll = self.time_source.now();
+        if !self.read_editor_log() {
+            return false;
+        }
+        self.refresh_editor_log_buffer();
+        true
+    }
+
+    /// Read the new lines of the log file into the view
+    ///
+    /// Returns true if any line was added.
+    fn read_editor_log(&mut self) -> bool {
+        let (Some(log), Some(view)) = (&self.editor_log.log, &mut self.editor_log.view) else {
+            return false;
+        };
+        match read_appended(log.path(), view.read_to) {
+            Ok(chunk) => view.append(chunk),
+            Err(e) => {
+                tracing::debug!("Failed to read log file {:?}: {}", log.path(), e);
+                false
+            }
+        }
+    }
+
+    /// Lines of the view at or above the level filter
+    fn visible_editor_log_lines(&self) -> impl Iterator<Item = &LogLine> {
+        let min_level = self.editor_log.min_level;
+        self.editor_log
+            .view
+            .iter()
+            .flat_map(|view| view.lines.iter())
+            .filter(move |line| line.level <= min_level)
+    }
+
+    /// Write the visible lines to the log buffer and color them
+    fn refresh_editor_log_buffer(&mut self) {
+        let Some(buffer_id) = self.editor_log.view.as_ref().map(|view| view.buffer_id) else {
+            return;
+        };
+        let mut text = String::new();
+        let mut colors = Vec::new();
+        for line in self.visible_editor_log_lines() {
+            if let Some(color) = level_color(line.level) {
+                colors.push((text.len()..text.len() + line.text.len(), color));
+            }
+            text.push_str(&line.text);
+            text.push('\n');
+        }
+        if let Err(e) =
+            self.set_virtual_buffer_content(buffer_id, vec![TextPropertyEntry::text(text)])
+        {
+            tracing::error!("Failed to show the editor log: {}", e);
+            return;
+        }
+
+        if let Some(state) = self.buffers.get_mut(&buffer_id) {
+            let namespace = OverlayNamespace::from_string(LEVEL_NAMESPACE.to_string());
+            state
+                .overlays
+                .clear_namespace(&namespace, &mut state.marker_list);
+            for (range, color) in colors {
+                let face = OverlayFace::Themed {
+                    style: ThemedStyle {
+                        fg: Some(DecorationColor::Named(color.to_string())),
+                        bg: None,
+                        add_modifier: Default::default(),
+                    },
+                };
+                let overlay =
+                    Overlay::with_namespace(&mut state.marker_list, range, face, namespace.clone());
+                state.overlays.add(overlay, &state.marker_list);
+            }
+        }
+
+        let follow_tail = self
+            .buffer_metadata
+            .get(&buffer_id)
+            .is_some_and(|m| m.follow_tail);
+        if follow_tail {
+            self.pin_follow_tail(buffer_id);
+        }
+    }
+
+    /// Prompt for the least severe level the log buffer shows
+    pub fn start_editor_log_level_prompt(&mut self) {
+        let current = self.editor_log.min_level;
+        let suggestions = LEVELS
+            .iter()
+            .map(|level| Suggestion {
+                description: (*level == current)
+                    .then(|| t!("editor_log.current_level").to_string()),
+                value: Some(level.to_string()),
+                ..Suggestion::new(level.to_string())
+            })
+            .collect();
+        self.start_prompt_with_suggestions(
+            t!("editor_log.level_prompt").to_string(),
+            PromptType::EditorLogLevel,
+            suggestions,
+        );
+    }
+
+    /// Show only the log lines at `input`'s level or more severe
+    pub(super) fn set_editor_log_level(&mut self, input: &str) {
+        let Some(level) = LEVELS
+            .into_iter()
+            .find(|level| level.as_str().eq_ignore_ascii_case(input.trim()))
+        else {
+            self.set_status_message(
+                t!("editor_log.invalid_level", level = input.trim()).to_string(),
+            );
+            return;
+        };
+        self.editor_log.min_level = level;
+        if self.editor_log.view.is_some() {
+            self.refresh_editor_log_buffer();
+        } else {
+            self.open_editor_log();
+        }
+        self.set_status_message(t!("editor_log.level_set", level = level.as_str()).to_string());
+    }
+
+    /// Open the log at the event of the warning log line under the cursor
+    pub fn open_editor_log_at_entry(&mut self) {
+        // Opened files have canonical paths
+        let warning_log = self
+            .warning_log
+            .as_ref()
+            .map(|(_, path)| path.canonicalize().unwrap_or_else(|_| path.clone()));
+        let is_warning_log = match (warning_log, self.active_state().buffer.file_path()) {
+            (Some(log_path), Some(path)) => log_path == path,
+            _ => false,
+        };
+        if !is_warning_log {
+            self.set_status_message(t!("editor_log.not_warning_log").to_string());
+            return;
+        }
+        let state = self.active_state();
+        let line = state
+            .buffer
+            .get_line_number(state.cursors.primary().position);
+        let line_text = state
+            .buffer
+            .get_line(line)
+            .map(|bytes| String::from_utf8_lossy(&bytes).into_owned())
+            .unwrap_or_default();
+        let Some((level, message)) = warning_entry(&line_text) else {
+            self.set_status_message(t!("editor_log.no_entry").to_string());
+            return;
+        };
+        let message = message.to_string();
+
+        if level > self.editor_log.min_level {
+            self.editor_log.min_level = level;
+        }
+        self.open_editor_log();
+        let Some(buffer_id) = self.editor_log.view.as_ref().map(|view| view.buffer_id) else {
+            return;
+        };
+        if self.active_buffer() != buffer_id {
+            return;
+        }
+        // The latest matching event is the likeliest to be this one
+        let found = self
+            .visible_editor_log_lines()
+            .enumerate()
+            .filter(|(_, line)| line.level == level && line.text.contains(&message))
+            .last()
+            .map(|(index, _)| index);
+        match found {
+            Some(index) => {
+                if let Some(metadata) = self.buffer_metadata.get_mut(&buffer_id) {
+                    metadata.follow_tail = false;
+                }
+                self.jump_to_line_column(Some(index + 1), None);
+            }
+            None => self.set_status_message(t!("editor_log.entry_not_found").to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chunk(text: &str, end: u64) -> LogChunk {
+        LogChunk {
+            text: text.to_string(),
+            end,
+            skipped: false,
+        }
+    }
+
+    #[test]
+    fn test_line_level() {
+        assert_eq!(
+            line_level("2026-01-02T03:04:05.678901Z  WARN fresh::app: slow"),
+            Some(Level::WARN)
+        );
+        assert_eq!(
+            line_level("2026-01-02 03:04:05.678 ERROR fresh::lsp: crashed"),
+            Some(Level::ERROR)
+        );
+        assert_eq!(line_level("    at some continuation"), None);
+        // Digits name levels too, but never in a log line's first words
+        assert_eq!(line_level("1 2 3"), None);
+    }
+
+    #[test]
+    fn test_warning_entry() {
+        assert_eq!(
+            warning_entry("2026-01-02 03:04:05.678 WARN fresh::app: slow render"),
+            Some((Level::WARN, "fresh::app: slow render"))
+        );
+        assert_eq!(
+            warning_entry(
+                "2026-01-02 03:04:05.678 ERROR fresh::lsp: crashed (suppressed 4 similar messages)\n"
+            ),
+            Some((Level::ERROR, "fresh::lsp: crashed"))
+        );
+        assert_eq!(warning_entry("not a log line"), None);
+    }
+
+    #[test]
+    fn test_append_keeps_partial_lines_and_levels() {
+        let mut view = LogView::new(BufferId(1));
+        assert!(view.append(chunk(
+            "t  INFO a: one\n\x1b[31mt ERROR\x1b[0m a: two\n  detail\nt  WA",
+            50
+        )));
+        assert_eq!(view.read_to, 50);
+        assert_eq!(view.partial, "t  WA");
+        let levels: Vec<_> = view.lines.iter().map(|l| l.level).collect();
+        assert_eq!(levels, [Level::INFO, Level::ERROR, Level::ERROR]);
+        assert_eq!(view.lines[1].text, "t ERROR a: two");
+
+        assert!(view.append(chunk("RN a: three\n", 62)));
+        assert_eq!(view.lines.back().unwrap().text, "t  WARN a: three");
+        assert_eq!(view.lines.back().unwrap().level, Level::WARN);
+        assert!(!view.append(chunk("t  INFO", 69)));
+    }
+
+    #[test]
+    fn test_append_after_skip_drops_the_cut_line() {
+        let mut view = LogView::new(BufferId(1));
+        view.append(chunk("t  INFO a: old\n", 15));
+        view.append(LogChunk {
+            text: "of a line\nt DEBUG a: new\n".to_string(),
+            end: 100,
+            skipped: true,
+        });
+        let texts: Vec<_> = view.lines.iter().map(|l| l.text.as_str()).collect();
+        assert_eq!(texts, ["t DEBUG a: new"]);
+    }
+
+    #[test]
+    fn test_read_appended_restarts_after_rotation() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("fresh.log");
+        std::fs::write(&path, "t  INFO a: one\nt  INFO a: two\n").unwrap();
+        let first = read_appended(&path, 0).unwrap();
+        assert_eq!(first.end, 30);
+        assert!(read_appended(&path, first.end).unwrap().text.is_empty());
+
+        std::fs::write(&path, "t  WARN a: new\n").unwrap();
+        let after = read_appended(&path, first.end).unwrap();
+        assert_eq!(after.text, "t  WARN a: new\n");
+        assert!(!after.skipped);
+    }
+}
@@ -0,0 +1,273 @@
+//! Recording keystrokes to the event log and replaying them
+//!
+//! With `--event-log`, every key the editor handles is written to the log
+//! with the time it was pressed on the editor's clock, counted from when
+//! logging started. `--replay` reads those keys back and presses them again
+//! on a virtual clock: before each key the clock is moved to the time it was
+//! recorded at and the event loop's timers are checked, so hover, auto-save,
+//! completion and notification timeouts fire between the same keys as they
+//! did when the log was recorded.
+
+use std::fs::File;
+use std::io::{self, Write};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use crossterm::event::{KeyCode, KeyModifiers};
+use serde_json::{json, Value};
+
+use super::Editor;
+use crate::model::event::EventLog;
+
+/// Where keystrokes are logged, and when logging started
+pub(super) struct KeystrokeLog {
+    file: File,
+    started: Instant,
+}
+
+/// A keystroke read back from an event log
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RecordedKeystroke {
+    /// Time since logging started at which the key was pressed
+    pub elapsed: Duration,
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+}
+
+/// Modifiers as they are named in the log
+const MODIFIER_NAMES: [(KeyModifiers, &str); 3] = [
+    (KeyModifiers::CONTROL, "ctrl"),
+    (KeyModifiers::ALT, "alt"),
+    (KeyModifiers::SHIFT, "shift"),
+];
+
+/// Keys other than characters and function keys, as they are named in the log
+const KEY_NAMES: [(KeyCode, &str); 16] = [
+    (KeyCode::Backspace, "Backspace"),
+    (KeyCode::Enter, "Enter"),
+    (KeyCode::Left, "Left"),
+    (KeyCode::Right, "Right"),
+    (KeyCode::Up, "Up"),
+    (KeyCode::Down, "Down"),
+    (KeyCode::Home, "Home"),
+    (KeyCode::End, "End"),
+    (KeyCode::PageUp, "PageUp"),
+    (KeyCode::PageDown, "PageDown"),
+    (KeyCode::Tab, "Tab"),
+    (KeyCode::BackTab, "BackTab"),
+    (KeyCode::Delete, "Delete"),
+    (KeyCode::Insert, "Insert"),
+    (KeyCode::Esc, "Esc"),
+    (KeyCode::Menu, "Menu"),
+];
+
+/// Name of `code` in the log: the character itself, "F5", or one of
+/// [`KEY_NAMES`]; None for keys that can't be replayed
+fn key_name(code: KeyCode) -> Option<String> {
+    match code {
+        KeyCode::Char(c) => Some(c.to_string()),
+        KeyCode::F(n) => Some(format!("F{n}")),
+        code => KEY_NAMES
+            .iter()
+            .find(|(key, _)| *key == code)
+            .map(|(_, name)| name.to_string()),
+    }
+}
+
+/// The key named `name` by [`key_name`]
+fn parse_key_name(name: &str) -> Option<KeyCode> {
+    let mut chars = name.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        return Some(KeyCode::Char(c));
+    }
+    if let Some(n) = name.strip_prefix('F').and_then(|n| n.parse().ok()) {
+        return Some(KeyCode::F(n));
+    }
+    KEY_NAMES
+        .iter()
+        .find(|(_, key)| *key == name)
+        .map(|(code, _)| *code)
+}
+
+/// Log line for a key pressed `elapsed` after logging started
+fn keystroke_entry(code: KeyCode, modifiers: KeyModifiers, elapsed: Duration) -> Value {
+    let modifier_names: Vec<&str> = MODIFIER_NAMES
+        .iter()
+        .filter(|(modifier, _)| modifiers.contains(*modifier))
+        .map(|(_, name)| *name)
+        .collect();
+    json!({
+        "type": "keystroke",
+        "timestamp": chrono::Local::now().to_rfc3339(),
+        "elapsed_ms": elapsed.as_millis() as u64,
+        "key": key_name(code).unwrap_or_else(|| format!("{code:?}")),
+        "modifiers": modifier_names,
+    })
+}
+
+impl RecordedKeystroke {
+    /// The keystroke a log line records, if it is one that can be replayed
+    fn from_entry(entry: &Value) -> Option<Self> {
+        if entry.get("type")?.as_str()? != "keystroke" {
+            return None;
+        }
+        let code = parse_key_name(entry.get("key")?.as_str()?)?;
+        let mut modifiers = KeyModifiers::NONE;
+        for name in entry.get("modifiers")?.as_array()? {
+            let (modifier, _) = MODIFIER_NAMES
+                .iter()
+                .find(|(_, modifier)| Some(*modifier) == name.as_str())?;
+            modifiers |= *modifier;
+        }
+        Some(Self {
+            elapsed: Duration::from_millis(entry.get("elapsed_ms")?.as_u64()?),
+            code,
+            modifiers,
+        })
+    }
+
+    /// The keystrokes of the event log at `path`, in the order they were
+    /// pressed; other lines of the log are skipped
+    pub fn read_all(path: &Path) -> io::Result<Vec<Self>> {
+        let content = std::fs::read_to_string(path)?;
+        Ok(content
+            .lines()
+            .filter(|line| !line.starts_with('#'))
+            .filter_map(|line| serde_json::from_str::<Value>(line).ok())
+            .filter_map(|entry| Self::from_entry(&entry))
+            .collect())
+    }
+}
+
+impl Editor {
+    /// Enable event log streaming to a file
+    pub fn enable_event_streaming<P: AsRef<Path>>(&mut self, path: P) -> io::Result<()> {
+        let file = EventLog::create_stream(path)?;
+        // Enable streaming for all existing event logs
+        for event_log in self.event_logs.values_mut() {
+            event_log.enable_streaming(file.try_clone()?);
+        }
+        self.keystroke_log = Some(KeystrokeLog {
+            file,
+            started: self.time_source.now(),
+        });
+        self.enable_input_latency_event_log();
+        Ok(())
+    }
+
+    /// Log a keystroke, with when it was pressed, if event logging is on
+    pub fn log_keystroke(&mut self, code: KeyCode, modifiers: KeyModifiers) {
+        let now = self.time_source.now();
+        let Some(log) = &mut self.keystroke_log else {
+            return;
+        };
+        let entry = keystroke_entry(code, modifiers, now.saturating_duration_since(log.started));
+        if let Err(e) = writeln!(log.file, "{entry}") {
+            tracing::trace!("Warning: Failed to write keystroke to stream: {e}");
+        }
+        if let Err(e) = log.file.flush() {
+            tracing::trace!("Warning: Failed to flush event stream: {e}");
+        }
+    }
+
+    /// Check the timers the event loop checks on each pass, returning true
+    /// if any of them changed what is shown
+    pub fn check_timers(&mut self) -> bool {
+        let mut changed = false;
+
+        // Check mouse hover timer for LSP hover requests
+        changed |= self.check_mouse_hover_timer();
+
+        // Open completion once typing paused
+        changed |= self.check_auto_completion_timer();
+
+        // Send full-sync language servers the text left due to them
+        self.check_lsp_sync_timer();
+
+        // Merge answers of several servers that slow ones held up
+        changed |= self.check_lsp_fanout_deadlines();
+
+        // Check semantic highlight debounce timer
+        changed |= self.check_semantic_highlight_timer();
+
+        // Refresh the input latency HUD and periodic latency log
+        changed |= self.check_input_latency_timer();
+
+        // Remove notifications whose timeout passed
+        changed |= self.check_notification_timer();
+
+        // Close plugin buffers left hidden and unused
+        changed |= self.close_idle_plugin_buffers();
+
+        // Index words of open buffers while the user is idle
+        self.index_words_when_idle();
+
+        // Write the usage statistics counted so far once input pauses
+        self.flush_usage_stats_when_idle();
+
+        if let Err(e) = self.auto_save_dirty_buffers() {
+            tracing::debug!("Auto-save error: {}", e);
+        }
+
+        changed
+    }
+
+    /// Press `keys` again, each once the editor's clock reaches the time it
+    /// was recorded at, counted from now
+    ///
+    /// `advance` moves the editor's clock forward; it has to be a virtual
+    /// one, such as [`crate::services::time_source::ReplayTimeSource`], for
+    /// the timers to fire between the same keys as when they were recorded.
+    pub fn replay_keystrokes(
+        &mut self,
+        keys: &[RecordedKeystroke],
+        mut advance: impl FnMut(Duration),
+    ) -> io::Result<()> {
+        let start = self.time_source.now();
+        for key in keys {
+            let elapsed = self.time_source.elapsed_since(start);
+            if key.elapsed > elapsed {
+                advance(key.elapsed - elapsed);
+            }
+            self.process_async_messages();
+            self.check_timers();
+            self.handle_key(key.code, key.modifiers)?;
+        }
+        self.process_async_messages();
+        self.check_timers();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keystrokes_read_back_as_logged() {
+        let keys = [
+            (KeyCode::Char('a'), KeyModifiers::NONE),
+            (KeyCode::Char('A'), KeyModifiers::SHIFT),
+            (KeyCode::Char('F'), KeyModifiers::SHIFT),
+            (KeyCode::F(5), KeyModifiers::NONE),
+            (KeyCode::Enter, KeyModifiers::CONTROL | KeyModifiers::ALT),
+            (KeyCode::BackTab, KeyModifiers::SHIFT),
+        ];
+        for (i, (code, modifiers)) in keys.into_iter().enumerate() {
+            let elapsed = Duration::from_millis(i as u64 * 250);
+            let entry = keystroke_entry(code, modifiers, elapsed);
+            assert_eq!(
+                RecordedKeystroke::from_entry(&entry),
+                Some(RecordedKeystroke {
+                    elapsed,
+                    code,
+                    modifiers
+                })
+            );
+        }
+
+        // Keys that can't be replayed are still logged, and skipped
+        let entry = keystroke_entry(KeyCode::CapsLock, KeyModifiers::NONE, Duration::ZERO);
+        assert_eq!(RecordedKeystroke::from_entry(&entry), None);
+    }
+}
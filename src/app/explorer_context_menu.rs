@@ -0,0 +1,356 @@
+//! Context menu of file explorer entries
+//!
+//! Right-clicking an entry, or running "File Explorer: Context Menu" on the
+//! selected one, opens a popup with the built-in actions followed by the
+//! actions plugins register with `editor.registerExplorerAction`. The popup
+//! is an ordinary list popup, so it takes the keys before the explorer does.
+//! Choosing a plugin action runs its own hook (see
+//! [`explorer_action_hook_name`]) with the entry's path.
+
+use rust_i18n::t;
+use std::path::{Path, PathBuf};
+
+use super::Editor;
+use crate::model::event::{PopupContentData, PopupData, PopupListItemData, PopupPositionData};
+use crate::primitives::display_width::str_width;
+use crate::services::plugins::api::ExplorerActionTarget;
+use crate::services::plugins::hooks::{explorer_action_hook_name, HookArgs};
+
+/// Popup item data of the built-in actions
+const OPEN_ACTION: &str = "open";
+const OPEN_IN_SPLIT_ACTION: &str = "open_in_split";
+const RENAME_ACTION: &str = "rename";
+const DELETE_ACTION: &str = "delete";
+const COPY_PATH_ACTION: &str = "copy_path";
+const REVEAL_ACTION: &str = "reveal";
+/// Popup item data of a plugin action is its id after this prefix
+const PLUGIN_ACTION_PREFIX: &str = "plugin:";
+
+/// A context menu entry registered by a plugin
+#[derive(Debug, Clone)]
+pub(super) struct ExplorerAction {
+    id: String,
+    label: String,
+    when: ExplorerActionTarget,
+    /// Plugin that owns the action
+    plugin: String,
+}
+
+/// The entry an open context menu acts on
+#[derive(Debug)]
+struct OpenMenu {
+    path: PathBuf,
+    is_dir: bool,
+    /// Title of the popup, used to recognize it
+    popup_title: String,
+}
+
+/// Plugin actions and the menu currently open
+#[derive(Debug, Default)]
+pub(super) struct ExplorerContextMenu {
+    /// Actions registered by plugins, in registration order
+    plugin_actions: Vec<ExplorerAction>,
+    open: Option<OpenMenu>,
+}
+
+impl Editor {
+    /// Add a plugin action to the context menu, or change one registered before
+    pub(super) fn handle_register_explorer_action(
+        &mut self,
+        id: String,
+        label: String,
+        when: ExplorerActionTarget,
+        plugin: String,
+    ) {
+        let action = ExplorerAction {
+            id,
+            label,
+            when,
+            plugin,
+        };
+        let actions = &mut self.explorer_context_menu.plugin_actions;
+        match actions.iter_mut().find(|a| a.id == action.id) {
+            Some(existing) => *existing = action,
+            None => actions.push(action),
+        }
+    }
+
+    /// Remove a plugin action from the context menu
+    pub(super) fn handle_unregister_explorer_action(&mut self, id: String) {
+        self.explorer_context_menu
+            .plugin_actions
+            .retain(|action| action.id != id);
+    }
+
+    /// Open the context menu on the explorer entry at a screen row, selecting it
+    pub(super) fn open_explorer_context_menu_at(&mut self, col: u16, row: u16) {
+        let Some(area) = self.cached_layout.file_explorer_area else {
+            return;
+        };
+        // The first row is the title, the entries start below it
+        if row <= area.y {
+            return;
+        }
+        let Some(explorer) = &mut self.file_explorer else {
+            return;
+        };
+        // Then the header row, if shown
+        let Some(index) = ((row - area.y - 1) as usize).checked_sub(explorer.header_rows()) else {
+            return;
+        };
+        let index = index + explorer.get_scroll_offset();
+        let Some(node_id) = explorer.get_node_at_index(index) else {
+            return;
+        };
+        explorer.set_selected(Some(node_id));
+        self.key_context = crate::input::keybindings::KeyContext::FileExplorer;
+        self.show_explorer_context_menu(col, row.saturating_add(1));
+    }
+
+    /// Open the context menu on the selected explorer entry, below its row
+    pub(super) fn open_explorer_context_menu_at_selection(&mut self) {
+        let (Some(area), Some(explorer)) =
+            (self.cached_layout.file_explorer_area, &self.file_explorer)
+        else {
+            return;
+        };
+        let Some(index) = explorer.get_selected_index() else {
+            return;
+        };
+        let offset = index.saturating_sub(explorer.get_scroll_offset()) as u16;
+        let row = area
+            .y
+            .saturating_add(1 + explorer.header_rows() as u16)
+            .saturating_add(offset);
+        self.show_explorer_context_menu(area.x.saturating_add(2), row.saturating_add(1));
+    }
+
+    fn show_explorer_context_menu(&mut self, x: u16, y: u16) {
+        let Some(explorer) = &self.file_explorer else {
+            return;
+        };
+        let Some(selected) = explorer.get_selected() else {
+            return;
+        };
+        let tree = explorer.tree();
+        let Some(node) = tree.get_node(selected) else {
+            return;
+        };
+        let path = node.entry.path.clone();
+        let is_dir = node.is_dir();
+        let is_root = selected == tree.root_id();
+        let title = node.entry.name.clone();
+
+        let items = self.explorer_context_menu_items(is_dir, is_root);
+        let width = items
+            .iter()
+            .map(|item| str_width(&item.text))
+            .chain(std::iter::once(str_width(&title)))
+            .max()
+            .unwrap_or(0)
+            .clamp(12, 40) as u16
+            + 4;
+        let popup = PopupData {
+            title: Some(title.clone()),
+            description: None,
+            transient: false,
+            content: PopupContentData::List { items, selected: 0 },
+            position: PopupPositionData::Fixed { x, y },
+            width,
+            max_height: 16,
+            bordered: true,
+        };
+
+        self.explorer_context_menu.open = Some(OpenMenu {
+            path,
+            is_dir,
+            popup_title: title,
+        });
+        self.show_popup(popup);
+    }
+
+    /// Entries of the menu on a file or directory: the built-in ones, then
+    /// the plugin ones offered on it
+    fn explorer_context_menu_items(&self, is_dir: bool, is_root: bool) -> Vec<PopupListItemData> {
+        let item = |text: String, data: String| PopupListItemData {
+            text,
+            detail: None,
+            icon: None,
+            data: Some(data),
+        };
+        let mut items = vec![item(
+            t!("menu.explorer.open").to_string(),
+            OPEN_ACTION.to_string(),
+        )];
+        if !is_dir {
+            items.push(item(
+                t!("menu.explorer.open_in_split").to_string(),
+                OPEN_IN_SPLIT_ACTION.to_string(),
+            ));
+        }
+        // The project root can't be renamed or deleted from the explorer
+        if !is_root {
+            items.push(item(
+                t!("menu.explorer.rename").to_string(),
+                RENAME_ACTION.to_string(),
+            ));
+            items.push(item(
+                t!("menu.explorer.delete").to_string(),
+                DELETE_ACTION.to_string(),
+            ));
+        }
+        items.push(item(
+            t!("menu.explorer.copy_path").to_string(),
+            COPY_PATH_ACTION.to_string(),
+        ));
+        items.push(item(
+            t!("menu.explorer.reveal").to_string(),
+            REVEAL_ACTION.to_string(),
+        ));
+        items.extend(
+            self.explorer_context_menu
+                .plugin_actions
+                .iter()
+                .filter(|action| action.when.matches(is_dir))
+                .map(|action| {
+                    item(
+                        action.label.clone(),
+                        format!("{}{}", PLUGIN_ACTION_PREFIX, action.id),
+                    )
+                }),
+        );
+        items
+    }
+
+    /// Whether the topmost popup is the explorer's context menu
+    pub(super) fn is_explorer_context_menu_popup(&self) -> bool {
+        let Some(open) = &self.explorer_context_menu.open else {
+            return false;
+        };
+        self.active_state()
+            .popups
+            .top()
+            .and_then(|popup| popup.title.as_ref())
+            .is_some_and(|title| *title == open.popup_title)
+    }
+
+    /// Run the action chosen in the context menu
+    ///
+    /// `None` means the menu was dismissed.
+    pub(super) fn handle_explorer_context_menu_response(&mut self, action: Option<&str>) {
+        let Some(OpenMenu { path, is_dir, .. }) = self.explorer_context_menu.open.take() else {
+            return;
+        };
+        let Some(action) = action else {
+            return;
+        };
+        if let Err(e) = self.run_explorer_context_menu_action(action, path, is_dir) {
+            self.set_status_message(t!("explorer.error", error = e.to_string()).to_string());
+        }
+    }
+
+    fn run_explorer_context_menu_action(
+        &mut self,
+        action: &str,
+        path: PathBuf,
+        is_dir: bool,
+    ) -> std::io::Result<()> {
+        if let Some(id) = action.strip_prefix(PLUGIN_ACTION_PREFIX) {
+            // The plugin may have been unloaded while the menu was open
+            let Some(action) = self
+                .explorer_context_menu
+                .plugin_actions
+                .iter()
+                .find(|a| a.id == id)
+            else {
+                return Ok(());
+            };
+            tracing::debug!(
+                "Explorer action '{}' of plugin '{}' on {}",
+                id,
+                action.plugin,
+                path.display()
+            );
+            self.plugin_manager.run_hook(
+                &explorer_action_hook_name(id),
+                HookArgs::ExplorerAction {
+                    action_id: id.to_string(),
+                    path,
+                    is_dir,
+                },
+            );
+            return Ok(());
+        }
+
+        match action {
+            COPY_PATH_ACTION => {
+                let text = path.to_string_lossy().to_string();
+                self.clipboard.copy(text.clone());
+                self.set_status_message(t!("explorer.copied_path", path = text).to_string());
+            }
+            REVEAL_ACTION => {
+                if let Err(e) = reveal_in_file_manager(&path, is_dir) {
+                    self.set_status_message(
+                        t!("explorer.error_revealing", error = e.to_string()).to_string(),
+                    );
+                }
+            }
+            OPEN_IN_SPLIT_ACTION => {
+                self.split_pane_vertical();
+                self.open_file(&path)?;
+                self.focus_editor();
+            }
+            _ => {
+                // The rest act on the selected entry
+                if let Some(explorer) = &mut self.file_explorer {
+                    explorer.navigate_to_path(&path);
+                }
+                match action {
+                    OPEN_ACTION => self.file_explorer_open_file()?,
+                    RENAME_ACTION => self.file_explorer_rename(),
+                    DELETE_ACTION => self.file_explorer_delete(),
+                    _ => {}
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Show a file or directory in the system's file manager
+fn reveal_in_file_manager(path: &Path, is_dir: bool) -> std::io::Result<()> {
+    #[cfg(target_os = "macos")]
+    let mut command = {
+        let _ = is_dir;
+        let mut command = std::process::Command::new("open");
+        command.arg("-R").arg(path);
+        command
+    };
+    #[cfg(windows)]
+    let mut command = {
+        let _ = is_dir;
+        let mut command = std::process::Command::new("explorer");
+        command.arg(format!("/select,{}", path.display()));
+        command
+    };
+    // Other file managers can't select an entry; open its directory instead
+    #[cfg(not(any(target_os = "macos", windows)))]
+    let mut command = {
+        let dir = if is_dir {
+            path
+        } else {
+            path.parent().unwrap_or(path)
+        };
+        let mut command = std::process::Command::new("xdg-open");
+        command.arg(dir);
+        command
+    };
+
+    let mut child = command
+        .stdin(std::process::Stdio::null())
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .spawn()?;
+    std::thread::spawn(move || child.wait());
+    Ok(())
+}
@@ -0,0 +1,195 @@
+//! Batched updates of the file explorer's tree
+//!
+//! Switching branches or running a build changes thousands of files at
+//! once. Instead of refreshing the tree on every change, changes are
+//! collected per directory and applied together once
+//! `file_explorer.refresh_debounce_ms` has passed since the first of them,
+//! by adding, removing and updating the entries involved in place. A file
+//! created and removed again within a batch is never shown.
+//!
+//! A directory that isn't expanded holds no entries to update: only its
+//! entry count badge is dropped, and it is read afresh when expanded.
+
+use super::Editor;
+use crate::services::path_watch::{PathChange, PathChangeKind};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+/// Changes waiting to be applied to one directory
+#[derive(Debug, Default)]
+struct DirtyDir {
+    /// Changed entries of the directory and what happened to them
+    changes: HashMap<PathBuf, PathChangeKind>,
+    /// Whether the directory changed in ways not known entry by entry, so
+    /// it has to be listed again
+    rescan: bool,
+}
+
+/// Batches applied to the explorer's tree so far
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ExplorerRefreshStats {
+    /// Batches applied
+    pub batches: usize,
+    /// Entries added, removed or updated by all batches
+    pub mutations: usize,
+}
+
+/// Explorer changes collected since the last batch
+#[derive(Debug, Default)]
+pub(super) struct ExplorerRefresh {
+    /// Pending changes by directory
+    dirty: HashMap<PathBuf, DirtyDir>,
+    /// When the oldest change of the pending batch came in
+    pending_since: Option<Instant>,
+    stats: ExplorerRefreshStats,
+}
+
+impl ExplorerRefresh {
+    fn dirty_dir(&mut self, dir: PathBuf, now: Instant) -> &mut DirtyDir {
+        self.pending_since.get_or_insert(now);
+        self.dirty.entry(dir).or_default()
+    }
+}
+
+impl Editor {
+    /// Queue changes on disk to show in the file explorer with the next batch
+    pub fn queue_file_explorer_changes(&mut self, changes: impl IntoIterator<Item = PathChange>) {
+        let Some(explorer) = &self.file_explorer else {
+            return;
+        };
+        let root = explorer.tree().root_path().to_path_buf();
+        let now = self.time_source.now();
+        let refresh = &mut self.explorer_refresh;
+
+        for PathChange { path, kind } in changes {
+            let Some(dir) = path.parent().filter(|dir| dir.starts_with(&root)) else {
+                continue;
+            };
+            let dir = dir.to_path_buf();
+            let dirty = refresh.dirty_dir(dir.clone(), now);
+            let kind = match dirty.changes.remove(&path) {
+                Some(older) => older.then(kind),
+                None => Some(kind),
+            };
+            match kind {
+                Some(kind) => {
+                    dirty.changes.insert(path, kind);
+                }
+                // Created and removed again: nothing to show
+                None if dirty.changes.is_empty() && !dirty.rescan => {
+                    refresh.dirty.remove(&dir);
+                }
+                None => {}
+            }
+        }
+        if refresh.dirty.is_empty() {
+            refresh.pending_since = None;
+        }
+    }
+
+    /// Queue listing a directory again with the next batch, for changes
+    /// known only by its modification time
+    pub(super) fn queue_file_explorer_rescan(&mut self, dir: PathBuf) {
+        let now = self.time_source.now();
+        self.explorer_refresh.dirty_dir(dir, now).rescan = true;
+    }
+
+    /// Apply the pending batch once it has waited long enough
+    ///
+    /// Returns true if the tree changed.
+    pub(super) fn flush_file_explorer_changes(&mut self) -> bool {
+        let Some(since) = self.explorer_refresh.pending_since else {
+            return false;
+        };
+        let debounce = Duration::from_millis(self.config.file_explorer.refresh_debounce_ms);
+        if self.time_source.elapsed_since(since) < debounce {
+            return false;
+        }
+        // The view is away while it is being expanded to the active file
+        if self.file_explorer.is_none() && self.file_explorer_sync_in_progress {
+            return false;
+        }
+        self.apply_file_explorer_changes()
+    }
+
+    /// Changes applied to the explorer's tree so far
+    pub fn file_explorer_refresh_stats(&self) -> ExplorerRefreshStats {
+        self.explorer_refresh.stats
+    }
+
+    fn apply_file_explorer_changes(&mut self) -> bool {
+        // Parents first, so the changes of a removed directory are skipped
+        let mut dirty: Vec<(PathBuf, DirtyDir)> = std::mem::take(&mut self.explorer_refresh.dirty)
+            .into_iter()
+            .collect();
+        dirty.sort_by(|(a, _), (b, _)| a.cmp(b));
+        self.explorer_refresh.pending_since = None;
+        let (Some(runtime), Some(explorer)) = (&self.tokio_runtime, &mut self.file_explorer) else {
+            return false;
+        };
+        let selected = explorer.get_selected();
+
+        let mut mutations = 0;
+        let mut still_loading = Vec::new();
+        for (dir, dirty_dir) in dirty {
+            explorer.invalidate_entry_count(&dir);
+            if dirty_dir.rescan {
+                explorer.invalidate_entry_metadata(&dir);
+            }
+            for path in dirty_dir.changes.keys() {
+                explorer.invalidate_entry_count(path);
+                explorer.invalidate_entry_metadata(path);
+            }
+            // Gone with a removed parent, or never read
+            let Some(node) = explorer.tree().get_node_by_path(&dir) else {
+                continue;
+            };
+            let dir_id = node.id;
+            if node.is_loading() {
+                still_loading.push((dir, dirty_dir));
+                continue;
+            }
+            if !node.is_expanded() {
+                continue;
+            }
+
+            if dirty_dir.rescan {
+                match runtime.block_on(self.fs_manager.list_dir_with_metadata(dir.clone())) {
+                    Ok(entries) => mutations += explorer.sync_children(dir_id, entries),
+                    Err(e) => tracing::warn!("Failed to list {:?}: {}", dir, e),
+                }
+                continue;
+            }
+            // What's on disk now decides, whatever the events said
+            for path in dirty_dir.changes.into_keys() {
+                let changed = match runtime.block_on(self.fs_manager.get_entry(&path)) {
+                    Ok(entry) => explorer.tree_mut().upsert_child(dir_id, entry),
+                    Err(_) => explorer.remove_entry(&path),
+                };
+                if changed {
+                    mutations += 1;
+                }
+            }
+        }
+
+        if explorer.get_selected() != selected {
+            explorer.update_scroll_for_selection();
+        }
+        explorer.clamp_scroll_offset();
+
+        // Directories being read take their changes to the next batch
+        let now = self.time_source.now();
+        for (dir, dirty_dir) in still_loading {
+            let pending = self.explorer_refresh.dirty_dir(dir, now);
+            pending.changes.extend(dirty_dir.changes);
+            pending.rescan |= dirty_dir.rescan;
+        }
+
+        let stats = &mut self.explorer_refresh.stats;
+        stats.batches += 1;
+        stats.mutations += mutations;
+        tracing::debug!("File explorer batch applied {} changes", mutations);
+        mutations > 0
+    }
+}
@@ -1,8 +1,11 @@
 use rust_i18n::t;
 
 use super::*;
-use crate::view::file_tree::TreeNode;
-use std::path::PathBuf;
+use crate::services::dir_loader::{DirLoad, DirProgress, DIR_BATCH_SIZE};
+use crate::services::plugins::api::FileExplorerInfo;
+use crate::services::worker_pool::JobPriority;
+use crate::view::file_tree::{NodeId, SortMode, TreeNode};
+use std::path::{Path, PathBuf};
 
 /// Get the parent directory path from a file tree node.
 /// If the node is a directory, returns its path. If it's a file, returns the parent directory.
@@ -86,34 +89,130 @@ impl Editor {
         if let Some(metadata) = self.buffer_metadata.get(&self.active_buffer()) {
             if let Some(file_path) = metadata.file_path() {
                 let target_path = file_path.clone();
-                let working_dir = self.working_dir.clone();
-
-                if target_path.starts_with(&working_dir) {
-                    if let Some(mut view) = self.file_explorer.take() {
-                        tracing::trace!(
-                            "sync_file_explorer_to_active_file: taking file_explorer for async expand to {:?}",
-                            target_path
-                        );
-                        if let (Some(runtime), Some(bridge)) =
-                            (&self.tokio_runtime, &self.async_bridge)
-                        {
-                            let sender = bridge.sender();
-                            // Mark sync as in progress so render knows to keep the layout
-                            self.file_explorer_sync_in_progress = true;
-
-                            runtime.spawn(async move {
-                                let _success = view.expand_and_select_file(&target_path).await;
-                                let _ = sender.send(AsyncMessage::FileExplorerExpandedToPath(view));
-                            });
-                        } else {
-                            self.file_explorer = Some(view);
-                        }
-                    }
-                }
+                self.reveal_in_file_explorer(target_path);
             }
         }
     }
 
+    /// Expand the ancestors of `target_path` in the file explorer and select it
+    ///
+    /// The expansion runs in the background: the explorer is taken out of the
+    /// editor and handed back through `FileExplorerExpandedToPath`. Does nothing
+    /// when the explorer is missing or the path is outside its root.
+    pub(crate) fn reveal_in_file_explorer(&mut self, target_path: PathBuf) {
+        let inside_root = self
+            .file_explorer
+            .as_ref()
+            .is_some_and(|view| target_path.starts_with(view.tree().root_path()));
+        if !inside_root {
+            return;
+        }
+
+        if let Some(mut view) = self.file_explorer.take() {
+            tracing::trace!(
+                "reveal_in_file_explorer: taking file_explorer for async expand to {:?}",
+                target_path
+            );
+            if let (Some(runtime), Some(bridge)) = (&self.tokio_runtime, &self.async_bridge) {
+                let sender = bridge.sender();
+                // Mark sync as in progress so render knows to keep the layout
+                self.file_explorer_sync_in_progress = true;
+
+                runtime.spawn(async move {
+                    let _success = view.expand_and_select_file(&target_path).await;
+                    let _ = sender.send(AsyncMessage::FileExplorerExpandedToPath(view));
+                });
+            } else {
+                self.file_explorer = Some(view);
+            }
+        }
+    }
+
+    /// Handle a plugin's request to reveal a path in the file explorer
+    ///
+    /// Unlike the sync to the active file this works while the explorer is
+    /// hidden. If the explorer doesn't exist yet or another reveal is running,
+    /// the path is revealed once the explorer is back.
+    pub(super) fn handle_reveal_in_file_explorer(&mut self, path: PathBuf) {
+        let path = if path.is_absolute() {
+            normalize_path(&path)
+        } else {
+            normalize_path(&self.working_dir.join(path))
+        };
+
+        if self.file_explorer_sync_in_progress {
+            self.pending_file_explorer_reveal = Some(path);
+        } else if self.file_explorer.is_none() {
+            self.pending_file_explorer_reveal = Some(path);
+            self.init_file_explorer();
+        } else {
+            self.reveal_in_file_explorer(path);
+        }
+    }
+
+    /// Reveal the path a plugin asked for while the explorer was unavailable
+    pub(super) fn reveal_pending_in_file_explorer(&mut self) {
+        if let Some(path) = self.pending_file_explorer_reveal.take() {
+            self.reveal_in_file_explorer(path);
+        }
+    }
+
+    /// Show `path` at the root of the file explorer, keeping the working directory
+    ///
+    /// The path goes through the same checks as switching projects.
+    pub(crate) fn set_file_explorer_root(&mut self, path: &Path) {
+        let Some(root) = self.resolve_project_dir(path) else {
+            return;
+        };
+        let root = root.canonicalize().unwrap_or(root);
+        self.pending_file_explorer_reveal = None;
+        self.init_file_explorer_at(root);
+    }
+
+    /// File explorer state as seen by plugins, None until the explorer exists
+    pub fn file_explorer_info(&self) -> Option<FileExplorerInfo> {
+        let view = self.file_explorer.as_ref()?;
+        let tree = view.tree();
+        let selected = view.get_selected().and_then(|id| tree.get_node(id));
+        let mut expanded_paths: Vec<PathBuf> = tree
+            .all_nodes()
+            .filter(|node| node.is_dir() && node.is_expanded())
+            .map(|node| node.entry.path.clone())
+            .collect();
+        expanded_paths.sort();
+        Some(FileExplorerInfo {
+            visible: self.file_explorer_visible,
+            root: tree.root_path().to_path_buf(),
+            selected_path: selected.map(|node| node.entry.path.clone()),
+            selected_is_dir: selected.is_some_and(|node| node.is_dir()),
+            expanded_paths,
+        })
+    }
+
+    /// Fire file_explorer_selection_changed if the selection moved since the last check
+    pub(super) fn check_file_explorer_selection(&mut self) {
+        // The explorer is away while a reveal runs; check again once it's back
+        if self.file_explorer_sync_in_progress {
+            return;
+        }
+        let Some(info) = self.file_explorer_info() else {
+            return;
+        };
+        if info.selected_path == self.previous_file_explorer_selection {
+            return;
+        }
+        self.previous_file_explorer_selection = info.selected_path.clone();
+        if self.plugin_manager.is_active() {
+            self.plugin_manager.run_hook(
+                "file_explorer_selection_changed",
+                crate::services::plugins::hooks::HookArgs::FileExplorerSelectionChanged {
+                    path: info.selected_path,
+                    is_dir: info.selected_is_dir,
+                },
+            );
+        }
+    }
+
     pub fn focus_file_explorer(&mut self) {
         if self.file_explorer_visible {
             // Dismiss transient popups and clear hover state when focusing file explorer
@@ -136,8 +235,10 @@ impl Editor {
     }
 
     pub(crate) fn init_file_explorer(&mut self) {
-        let root_path = self.working_dir.clone();
+        self.init_file_explorer_at(self.working_dir.clone());
+    }
 
+    fn init_file_explorer_at(&mut self, root_path: PathBuf) {
         if let (Some(runtime), Some(bridge)) = (&self.tokio_runtime, &self.async_bridge) {
             let fs_manager = Arc::clone(&self.fs_manager);
             let sender = bridge.sender();
@@ -231,10 +332,14 @@ impl Editor {
             return;
         };
 
-        let (is_dir, is_expanded, name) = if let Some(explorer) = &self.file_explorer {
+        let (is_dir, is_open, name) = if let Some(explorer) = &self.file_explorer {
             let node = explorer.tree().get_node(selected_id);
             if let Some(node) = node {
-                (node.is_dir(), node.is_expanded(), node.entry.name.clone())
+                (
+                    node.is_dir(),
+                    node.is_expanded() || node.is_loading(),
+                    node.entry.name.clone(),
+                )
             } else {
                 return;
             }
@@ -246,61 +351,270 @@ impl Editor {
             return;
         }
 
-        let status_msg = if is_expanded {
-            t!("explorer.collapsing").to_string()
+        if is_open {
+            // Collapsing a directory that is still being listed cancels the listing
+            self.dir_loads.remove(&selected_id);
+            if let Some(explorer) = &mut self.file_explorer {
+                explorer.tree_mut().collapse_node(selected_id);
+            }
+            self.set_status_message(t!("explorer.collapsed", name = &name).to_string());
         } else {
-            t!("explorer.loading_dir", name = &name).to_string()
+            self.set_status_message(t!("explorer.loading_dir", name = &name).to_string());
+            self.file_explorer_expand_dir(selected_id);
+        }
+    }
+
+    /// Expand a directory, listing its entries on the worker pool
+    ///
+    /// Waits briefly so small directories expand in one step; larger ones
+    /// show their first entries with a loading footer and fill in as the
+    /// remaining batches arrive (see `poll_dir_loads`).
+    fn file_explorer_expand_dir(&mut self, node_id: NodeId) {
+        let (Some(runtime), Some(explorer)) = (&self.tokio_runtime, &mut self.file_explorer) else {
+            return;
         };
-        self.set_status_message(status_msg);
 
-        if let (Some(runtime), Some(explorer)) = (&self.tokio_runtime, &mut self.file_explorer) {
-            let tree = explorer.tree_mut();
-            let result = runtime.block_on(tree.toggle_node(selected_id));
+        let dir_path = match explorer.tree_mut().begin_expand(node_id) {
+            Ok(path) => path,
+            Err(e) => {
+                self.set_status_message(t!("explorer.error", error = e.to_string()).to_string());
+                return;
+            }
+        };
+        if let Err(e) = explorer.load_gitignore_for_dir(&dir_path) {
+            tracing::warn!("Failed to load .gitignore from {:?}: {}", dir_path, e);
+        }
+
+        let wake = self.async_bridge.as_ref().map(|bridge| bridge.sender());
+        let mut load = DirLoad::start(
+            &self.worker_pool,
+            runtime.handle().clone(),
+            Arc::clone(&self.fs_manager),
+            &dir_path,
+            wake,
+        );
+        let progress = load.wait(self.quick_load_timeout);
+        if !self.apply_dir_progress(node_id, &load, progress) {
+            self.dir_loads.insert(node_id, load);
+        }
+    }
+
+    /// Add newly listed entries to a directory being expanded
+    ///
+    /// Returns true once the listing has ended.
+    fn apply_dir_progress(
+        &mut self,
+        node_id: NodeId,
+        load: &DirLoad,
+        progress: DirProgress,
+    ) -> bool {
+        let Some(explorer) = &mut self.file_explorer else {
+            return true;
+        };
+        explorer.append_children(node_id, progress.entries);
+        let name = explorer
+            .tree()
+            .get_node(node_id)
+            .map(|node| node.entry.name.clone())
+            .unwrap_or_default();
 
-            let final_name = explorer
+        let (message, finished) = match progress.finished {
+            None => (
+                t!(
+                    "explorer.loading_entries",
+                    name = &name,
+                    count = load.loaded()
+                )
+                .to_string(),
+                false,
+            ),
+            Some(Ok(())) => {
+                explorer.tree_mut().finish_expand(node_id);
+                (t!("explorer.expanded", name = &name).to_string(), true)
+            }
+            Some(Err(e)) => {
+                tracing::warn!("Failed to list {:?}: {}", load.path(), e);
+                explorer.tree_mut().fail_expand(node_id, &e);
+                (
+                    t!("explorer.error", error = e.to_string()).to_string(),
+                    true,
+                )
+            }
+        };
+        self.set_status_message(message);
+        finished
+    }
+
+    /// Add entries that arrived for directories being listed in the background
+    ///
+    /// Returns true if the tree changed.
+    pub(super) fn poll_dir_loads(&mut self) -> bool {
+        if self.dir_loads.is_empty() {
+            return false;
+        }
+        // The view is away while it is being expanded to the active file
+        let Some(explorer) = &self.file_explorer else {
+            return false;
+        };
+
+        // Drop listings whose directory was collapsed, refreshed or removed
+        self.dir_loads.retain(|node_id, _| {
+            explorer
                 .tree()
-                .get_node(selected_id)
-                .map(|n| n.entry.name.clone());
-            let final_expanded = explorer
+                .get_node(*node_id)
+                .is_some_and(|node| node.is_loading())
+        });
+
+        let mut changed = false;
+        let node_ids: Vec<NodeId> = self.dir_loads.keys().copied().collect();
+        for node_id in node_ids {
+            let Some(mut load) = self.dir_loads.remove(&node_id) else {
+                continue;
+            };
+            let progress = load.poll();
+            if progress.entries.is_empty() && progress.finished.is_none() {
+                self.dir_loads.insert(node_id, load);
+                continue;
+            }
+            changed = true;
+            if !self.apply_dir_progress(node_id, &load, progress) {
+                self.dir_loads.insert(node_id, load);
+            }
+        }
+        changed
+    }
+
+    /// Show every entry of the directory truncated at the display cap
+    ///
+    /// Acts on the selected directory or the nearest directory containing
+    /// the selection.
+    pub fn file_explorer_show_all_entries(&mut self) {
+        let Some(explorer) = &mut self.file_explorer else {
+            return;
+        };
+        let capped_dir = explorer.get_selected().and_then(|selected| {
+            explorer
                 .tree()
-                .get_node(selected_id)
-                .map(|n| n.is_expanded())
-                .unwrap_or(false);
+                .get_ancestors(selected)
+                .into_iter()
+                .rev()
+                .find(|id| explorer.tree().hidden_child_count(*id) > 0)
+        });
+
+        let message = match capped_dir {
+            Some(dir) => {
+                explorer.tree_mut().show_all_children(dir);
+                let node = explorer.tree().get_node(dir);
+                t!(
+                    "explorer.showing_all_entries",
+                    name = node.map(|n| n.entry.name.as_str()).unwrap_or_default(),
+                    count = node.map_or(0, |n| n.children.len())
+                )
+                .to_string()
+            }
+            None => t!("explorer.no_hidden_entries").to_string(),
+        };
+        self.set_status_message(message);
+    }
 
-            match result {
-                Ok(()) => {
-                    if final_expanded {
-                        let dir_path = explorer
-                            .tree()
-                            .get_node(selected_id)
-                            .map(|n| n.entry.path.clone());
-
-                        if let Some(dir_path) = dir_path {
-                            if let Err(e) = explorer.load_gitignore_for_dir(&dir_path) {
-                                tracing::warn!(
-                                    "Failed to load .gitignore from {:?}: {}",
-                                    dir_path,
-                                    e
-                                );
-                            }
-                        }
-                    }
+    /// Count entries of collapsed directories on screen for the entry count badge
+    pub(super) fn request_entry_counts(&mut self) {
+        if !self.config.file_explorer.show_entry_counts {
+            return;
+        }
+        let (Some(runtime), Some(explorer)) = (&self.tokio_runtime, &self.file_explorer) else {
+            return;
+        };
 
-                    if let Some(name) = final_name {
-                        let msg = if final_expanded {
-                            t!("explorer.expanded", name = &name).to_string()
-                        } else {
-                            t!("explorer.collapsed", name = &name).to_string()
-                        };
-                        self.set_status_message(msg);
-                    }
-                }
-                Err(e) => {
-                    self.set_status_message(
-                        t!("explorer.error", error = e.to_string()).to_string(),
-                    );
-                }
-            }
+        let tree = explorer.tree();
+        let uncounted: Vec<PathBuf> = tree
+            .get_visible_rows()
+            .into_iter()
+            .skip(explorer.get_scroll_offset())
+            .take(explorer.viewport_height)
+            .filter_map(|row| row.node_id().and_then(|id| tree.get_node(id)))
+            .filter(|node| node.is_dir() && node.is_collapsed())
+            .map(|node| node.entry.path.clone())
+            .filter(|path| {
+                explorer.entry_count(path).is_none() && !self.pending_entry_counts.contains(path)
+            })
+            .collect();
+
+        for path in uncounted {
+            self.pending_entry_counts.insert(path.clone());
+            let handle = runtime.handle().clone();
+            let fs_manager = Arc::clone(&self.fs_manager);
+            self.worker_pool
+                .submit("dir-entry-count", JobPriority::Background, move |_| {
+                    handle.block_on(async {
+                        let modified = fs_manager
+                            .get_single_metadata(&path)
+                            .await
+                            .ok()
+                            .and_then(|metadata| metadata.modified);
+                        let count = fs_manager.list_dir(path.clone()).await.ok()?.len();
+                        Some(AsyncMessage::FileExplorerEntryCount {
+                            path,
+                            count,
+                            modified,
+                        })
+                    })
+                });
+        }
+    }
+
+    /// Fetch the metadata of entries listed without it
+    ///
+    /// Only the entries on screen are stat'ed, unless the sort uses
+    /// metadata, which needs it for every loaded entry. Entries of a
+    /// directory still being listed wait until it is done, since arriving
+    /// batches keep moving them on and off the screen.
+    pub(super) fn request_entry_metadata(&mut self) {
+        let (Some(runtime), Some(explorer)) = (&self.tokio_runtime, &self.file_explorer) else {
+            return;
+        };
+
+        let tree = explorer.tree();
+        let missing: Vec<PathBuf> = if explorer.sort().uses_metadata() {
+            tree.all_nodes()
+                .filter(|node| node.entry.metadata.is_none())
+                .map(|node| node.entry.path.clone())
+                .collect()
+        } else {
+            tree.get_visible_rows()
+                .into_iter()
+                .skip(explorer.get_scroll_offset())
+                .take(explorer.viewport_height)
+                .filter_map(|row| row.node_id().and_then(|id| tree.get_node(id)))
+                .filter(|node| node.entry.metadata.is_none())
+                .filter(|node| {
+                    !node
+                        .parent
+                        .and_then(|parent| tree.get_node(parent))
+                        .is_some_and(|parent| parent.is_loading())
+                })
+                .map(|node| node.entry.path.clone())
+                .collect()
+        };
+        let missing: Vec<PathBuf> = missing
+            .into_iter()
+            .filter(|path| !self.pending_entry_metadata.contains(path))
+            .collect();
+
+        for paths in missing.chunks(DIR_BATCH_SIZE) {
+            self.pending_entry_metadata.extend(paths.iter().cloned());
+            let paths = paths.to_vec();
+            let handle = runtime.handle().clone();
+            let fs_manager = Arc::clone(&self.fs_manager);
+            self.worker_pool
+                .submit("entry-metadata", JobPriority::Background, move |_| {
+                    let metadata = handle.block_on(fs_manager.get_metadata(paths.clone()));
+                    let entries = paths
+                        .into_iter()
+                        .zip(metadata.into_iter().map(Result::ok))
+                        .collect();
+                    Some(AsyncMessage::FileExplorerEntryMetadata { entries })
+                });
         }
     }
 
@@ -598,6 +912,40 @@ impl Editor {
         }
     }
 
+    /// Show or hide the size and modified time columns
+    pub fn file_explorer_toggle_details(&mut self) {
+        self.file_explorer_details = !self.file_explorer_details;
+        if let Some(explorer) = &mut self.file_explorer {
+            explorer.set_show_details(self.file_explorer_details);
+        }
+        let msg = if self.file_explorer_details {
+            t!("explorer.showing_details")
+        } else {
+            t!("explorer.hiding_details")
+        };
+        self.set_status_message(msg.to_string());
+    }
+
+    /// Sort the explorer's entries the next way: by name, modified time or
+    /// size, each in both directions
+    pub fn file_explorer_cycle_sort(&mut self) {
+        let sort = self.file_explorer_sort.next();
+        self.file_explorer_sort = sort;
+        if let Some(explorer) = &mut self.file_explorer {
+            explorer.set_sort(sort);
+            explorer.update_scroll_for_selection();
+        }
+        let msg = match (sort.mode, sort.descending) {
+            (SortMode::Name, false) => t!("explorer.sorted_name_asc"),
+            (SortMode::Name, true) => t!("explorer.sorted_name_desc"),
+            (SortMode::Modified, true) => t!("explorer.sorted_modified_desc"),
+            (SortMode::Modified, false) => t!("explorer.sorted_modified_asc"),
+            (SortMode::Size, true) => t!("explorer.sorted_size_desc"),
+            (SortMode::Size, false) => t!("explorer.sorted_size_asc"),
+        };
+        self.set_status_message(msg.to_string());
+    }
+
     pub fn file_explorer_toggle_gitignored(&mut self) {
         if let Some(explorer) = &mut self.file_explorer {
             explorer.toggle_show_gitignored();
@@ -0,0 +1,423 @@
+//! Background file loading for the Editor.
+//!
+//! Opening a file reads it on the worker pool. Loads that finish within
+//! [`QUICK_LOAD_TIMEOUT`] behave exactly like a synchronous open; slower ones
+//! (network mounts, huge files on slow disks) get a read-only placeholder
+//! buffer that is swapped for the real contents when the read completes.
+//! Edits typed into the placeholder are queued and replayed afterwards.
+
+use std::io;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+use rust_i18n::t;
+
+use crate::input::keybindings::Action;
+use crate::model::buffer::{Buffer, DEFAULT_LARGE_FILE_THRESHOLD};
+use crate::model::encoding::TextEncoding;
+use crate::model::event::{BufferId, SplitId};
+use crate::services::file_loader::{FileLoad, FileSource};
+use crate::session::SerializedFileState;
+use crate::state::EditorState;
+
+use super::Editor;
+
+/// How long opening a file waits for its contents before showing a placeholder
+pub(crate) const QUICK_LOAD_TIMEOUT: Duration = Duration::from_millis(50);
+
+/// A file buffer whose contents are not available
+pub(crate) enum BufferLoad {
+    /// Still being read in the background
+    Loading(Box<PendingLoad>),
+    /// The read failed; the buffer holds none of the file and must not be saved
+    Failed(String),
+}
+
+/// Bookkeeping for a file that is still being read
+pub(crate) struct PendingLoad {
+    load: FileLoad,
+    /// Split the file was opened in
+    split_id: SplitId,
+    /// Edits made before the contents arrived, replayed once they have
+    queued_actions: Vec<Action>,
+    /// Cursor and scroll from session restore, applied once loaded
+    restored_state: Option<(SplitId, SerializedFileState)>,
+    /// Line/column requested on the command line, applied once loaded
+    goto: Option<(usize, Option<usize>)>,
+    /// Last progress percentage shown in the status bar
+    shown_percent: Option<u8>,
+}
+
+impl Editor {
+    /// Replace where file contents are read from (used by tests to simulate slow mounts)
+    pub fn set_file_source(&mut self, source: Arc<dyn FileSource>) {
+        self.file_source = source;
+    }
+
+    /// Set how long opening a file blocks before falling back to a loading placeholder
+    pub fn set_quick_load_timeout(&mut self, timeout: Duration) {
+        self.quick_load_timeout = timeout;
+    }
+
+    /// Whether a buffer's file contents are still being read
+    pub fn is_buffer_loading(&self, buffer_id: BufferId) -> bool {
+        matches!(
+            self.file_loads.get(&buffer_id),
+            Some(BufferLoad::Loading(_))
+        )
+    }
+
+    /// Whether reading a buffer's file failed
+    pub fn buffer_load_failed(&self, buffer_id: BufferId) -> bool {
+        matches!(self.file_loads.get(&buffer_id), Some(BufferLoad::Failed(_)))
+    }
+
+    /// Create the state for an existing file
+    ///
+    /// Returns a placeholder state together with the pending load when the
+    /// contents don't arrive within the quick-load window.
+    pub(super) fn load_file_state(
+        &mut self,
+        path: &Path,
+    ) -> io::Result<(EditorState, Option<FileLoad>)> {
+        let threshold = match self.config.editor.large_file_threshold_bytes as usize {
+            0 => DEFAULT_LARGE_FILE_THRESHOLD,
+            threshold => threshold,
+        };
+        // Remote files are read through their provider, always in full
+        let remote = self.remote_file_provider(path)?;
+        let source: Arc<dyn FileSource> = match &remote {
+            Some(provider) => Arc::clone(provider) as Arc<dyn FileSource>,
+            None => Arc::clone(&self.file_source),
+        };
+        let size = source.size(path)?;
+
+        // Large files are opened lazily and only read what is displayed
+        if remote.is_none() && size as usize >= threshold {
+            let state = EditorState::from_file_with_languages(
+                path,
+                self.terminal_width,
+                self.terminal_height,
+                threshold,
+                &self.grammar_registry,
+                &self.config.languages,
+            )?;
+            return Ok((state, None));
+        }
+
+        let wake = self.async_bridge.as_ref().map(|bridge| bridge.sender());
+        let mut load = FileLoad::start(&self.worker_pool, source, path, size, wake);
+        match load.wait(self.quick_load_timeout) {
+            Some(result) => Ok((self.file_state_from_contents(path, result?), None)),
+            None => {
+                let text = t!("buffer.loading_placeholder", name = display_file_name(path));
+                Ok((self.placeholder_state(path, &text), Some(load)))
+            }
+        }
+    }
+
+    /// Start tracking a load whose buffer is showing the placeholder
+    pub(super) fn track_file_load(
+        &mut self,
+        buffer_id: BufferId,
+        load: FileLoad,
+        split_id: SplitId,
+    ) {
+        tracing::info!("Loading {} in the background", load.path().display());
+        self.file_loads.insert(
+            buffer_id,
+            BufferLoad::Loading(Box::new(PendingLoad {
+                load,
+                split_id,
+                queued_actions: Vec::new(),
+                restored_state: None,
+                goto: None,
+                // Opening the file already reported the load as started
+                shown_percent: Some(0),
+            })),
+        );
+    }
+
+    /// Forget any load state for a buffer that is being closed
+    ///
+    /// Returns true if the buffer never received its file contents.
+    pub(super) fn discard_file_load(&mut self, buffer_id: BufferId) -> bool {
+        self.file_loads.remove(&buffer_id).is_some()
+    }
+
+    /// Defer a session-restored cursor/scroll position until the buffer has loaded
+    ///
+    /// Returns false if the buffer is not loading and the caller should apply it now.
+    pub(super) fn defer_restored_file_state(
+        &mut self,
+        buffer_id: BufferId,
+        split_id: SplitId,
+        file_state: &SerializedFileState,
+    ) -> bool {
+        match self.file_loads.get_mut(&buffer_id) {
+            Some(BufferLoad::Loading(pending)) => {
+                pending.restored_state = Some((split_id, file_state.clone()));
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Defer a go-to-line until the active buffer has loaded
+    ///
+    /// Returns false if the buffer is not loading and the caller should move now.
+    pub(super) fn defer_goto_line(&mut self, line: usize, column: Option<usize>) -> bool {
+        let buffer_id = self.active_buffer();
+        match self.file_loads.get_mut(&buffer_id) {
+            Some(BufferLoad::Loading(pending)) => {
+                pending.goto = Some((line, column));
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Check whether an edit to the active buffer has to be held back
+    ///
+    /// While the buffer is loading the edit is queued and replayed once the
+    /// contents arrive; read-only buffers reject it with a status message.
+    pub(super) fn edit_blocked(&mut self, action: &Action) -> bool {
+        let buffer_id = self.active_buffer();
+        if let Some(BufferLoad::Loading(pending)) = self.file_loads.get_mut(&buffer_id) {
+            pending.queued_actions.push(action.clone());
+            return true;
+        }
+        if self.is_editing_disabled() {
+            self.set_status_message(t!("buffer.editing_disabled").to_string());
+            return true;
+        }
+        false
+    }
+
+    /// Refuse to save a buffer that doesn't hold its file's contents
+    ///
+    /// Returns false (and explains why in the status bar) if the buffer is
+    /// still loading or failed to load.
+    pub(super) fn check_buffer_loaded(&mut self, buffer_id: BufferId) -> bool {
+        let message = match self.file_loads.get(&buffer_id) {
+            None => return true,
+            Some(BufferLoad::Loading(_)) => t!("buffer.still_loading").to_string(),
+            Some(BufferLoad::Failed(error)) => {
+                t!("buffer.load_failed", error = error.as_str()).to_string()
+            }
+        };
+        self.set_status_message(message);
+        false
+    }
+
+    /// Collect progress from background loads and install finished ones
+    ///
+    /// Returns true if anything visible changed.
+    pub(super) fn poll_file_loads(&mut self) -> bool {
+        if self.file_loads.is_empty() {
+            return false;
+        }
+
+        let active_buffer = self.active_buffer();
+        let mut finished = Vec::new();
+        let mut progress_message = None;
+
+        for (buffer_id, load) in self.file_loads.iter_mut() {
+            let BufferLoad::Loading(pending) = load else {
+                continue;
+            };
+            if let Some(result) = pending.load.poll() {
+                finished.push((*buffer_id, result));
+                continue;
+            }
+            let percent = pending.load.percent();
+            if *buffer_id == active_buffer && pending.shown_percent != Some(percent) {
+                pending.shown_percent = Some(percent);
+                progress_message = Some(
+                    t!(
+                        "buffer.loading_progress",
+                        name = display_file_name(pending.load.path()),
+                        percent = percent
+                    )
+                    .to_string(),
+                );
+            }
+        }
+
+        let changed = progress_message.is_some() || !finished.is_empty();
+        if let Some(message) = progress_message {
+            self.set_status_message(message);
+        }
+        for (buffer_id, result) in finished {
+            let Some(BufferLoad::Loading(pending)) = self.file_loads.remove(&buffer_id) else {
+                continue;
+            };
+            match result {
+                Ok(contents) => self.complete_file_load(buffer_id, *pending, contents),
+                Err(e) => self.fail_file_load(buffer_id, &pending, e),
+            }
+        }
+        changed
+    }
+
+    /// Swap the placeholder for the loaded contents and replay queued edits
+    fn complete_file_load(&mut self, buffer_id: BufferId, pending: PendingLoad, contents: Vec<u8>) {
+        let PendingLoad {
+            load,
+            split_id,
+            queued_actions,
+            restored_state,
+            goto,
+            ..
+        } = pending;
+        let path = load.path().to_path_buf();
+        drop(load);
+
+        if !self.buffers.contains_key(&buffer_id) {
+            return;
+        }
+        tracing::info!("Finished loading {}", path.display());
+
+        let mut state = self.file_state_from_contents(&path, contents);
+        self.configure_file_state(&mut state, &path);
+        self.buffers.insert(buffer_id, state);
+        self.finish_file_open(buffer_id, &path, split_id);
+
+        if let Some((split_id, file_state)) = restored_state {
+            self.apply_restored_file_state(split_id, buffer_id, &file_state);
+        }
+
+        if self.active_buffer() != buffer_id {
+            if !queued_actions.is_empty() {
+                tracing::warn!(
+                    "Dropping {} edits queued for {} after switching away",
+                    queued_actions.len(),
+                    path.display()
+                );
+            }
+            return;
+        }
+
+        if let Some((line, column)) = goto {
+            self.goto_line_col(line, column);
+        }
+        self.set_status_message(t!("buffer.opened", name = display_file_name(&path)).to_string());
+        for action in queued_actions {
+            if let Err(e) = self.handle_action(action) {
+                tracing::warn!("Failed to replay edit queued during load: {}", e);
+            }
+        }
+    }
+
+    /// Mark the placeholder buffer as errored
+    fn fail_file_load(&mut self, buffer_id: BufferId, pending: &PendingLoad, error: io::Error) {
+        let path = pending.load.path();
+        tracing::error!("Failed to load {}: {}", path.display(), error);
+        let error = error.to_string();
+
+        if self.buffers.contains_key(&buffer_id) {
+            let text = t!(
+                "buffer.load_failed_placeholder",
+                name = display_file_name(path),
+                error = error.as_str()
+            )
+            .to_string();
+            let mut state = self.placeholder_state(path, &text);
+            self.configure_file_state(&mut state, path);
+            self.buffers.insert(buffer_id, state);
+        }
+        if !pending.queued_actions.is_empty() {
+            tracing::warn!(
+                "Dropping {} edits queued for {}",
+                pending.queued_actions.len(),
+                path.display()
+            );
+        }
+        if self.active_buffer() == buffer_id {
+            self.set_status_message(t!("buffer.load_failed", error = error.as_str()).to_string());
+        }
+        self.file_loads.insert(buffer_id, BufferLoad::Failed(error));
+    }
+
+    fn file_state_from_contents(&mut self, path: &Path, contents: Vec<u8>) -> EditorState {
+        let (contents, encoding) = self.decode_file_contents(path, contents);
+        let mut buffer = Buffer::from_file_contents(path, contents);
+        buffer.set_encoding(encoding);
+        EditorState::from_buffer_with_languages(
+            buffer,
+            path,
+            &self.grammar_registry,
+            &self.config.languages,
+        )
+    }
+
+    /// Decode the contents of the file at `path` into UTF-8 with the encoding
+    /// `.gitattributes` gives it, returning the encoding to save it back with
+    pub(super) fn decode_file_contents(
+        &mut self,
+        path: &Path,
+        contents: Vec<u8>,
+    ) -> (Vec<u8>, TextEncoding) {
+        // UTF-16 text has NUL bytes for every ASCII character, so a NUL-free
+        // UTF-8 file hasn't been converted yet and is left for save to encode
+        let already_utf8 = !contents.contains(&0) && std::str::from_utf8(&contents).is_ok();
+        match self.working_tree_encoding(path) {
+            Some(encoding) if !already_utf8 => encoding.decode(&contents).unwrap_or_else(|| {
+                tracing::warn!(
+                    "{} is not valid {} as .gitattributes says; opening it as is",
+                    path.display(),
+                    encoding.display_name()
+                );
+                (contents, TextEncoding::Utf8)
+            }),
+            _ => (contents, TextEncoding::Utf8),
+        }
+    }
+
+    /// The non-UTF-8 `working-tree-encoding` `.gitattributes` gives a file
+    fn working_tree_encoding(&mut self, path: &Path) -> Option<TextEncoding> {
+        let name = self.git_attributes.lookup(path).working_tree_encoding?;
+        TextEncoding::from_name(&name).filter(|encoding| *encoding != TextEncoding::Utf8)
+    }
+
+    /// Read a file again for reverting, decoding it as when it was opened
+    pub(super) fn reload_file_state(&mut self, path: &Path) -> io::Result<EditorState> {
+        let state = EditorState::from_file_with_languages(
+            path,
+            self.terminal_width,
+            self.terminal_height,
+            self.config.editor.large_file_threshold_bytes as usize,
+            &self.grammar_registry,
+            &self.config.languages,
+        )?;
+        if state.buffer.is_large_file() || self.working_tree_encoding(path).is_none() {
+            return Ok(state);
+        }
+        let contents = state.buffer.get_all_text().unwrap_or_default();
+        Ok(self.file_state_from_contents(path, contents))
+    }
+
+    /// Read-only state showing `text` in place of a file's contents
+    ///
+    /// Saving is refused while the buffer is in `file_loads`, so the text
+    /// never reaches the file.
+    fn placeholder_state(&self, path: &Path, text: &str) -> EditorState {
+        let mut state = EditorState::new(
+            self.terminal_width,
+            self.terminal_height,
+            self.config.editor.large_file_threshold_bytes as usize,
+        );
+        state.buffer = Buffer::from_str(text, 0);
+        state.buffer.set_file_path(path.to_path_buf());
+        state.marker_list.adjust_for_insert(0, text.len());
+        state.editing_disabled = true;
+        state
+    }
+}
+
+pub(super) fn display_file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| path.display().to_string())
+}
@@ -6,18 +6,20 @@
 use super::file_open::{FileOpenSection, SortMode};
 use super::Editor;
 use crate::input::keybindings::Action;
+use crate::services::file_provider::is_remote_path;
 use crate::view::prompt::PromptType;
 use rust_i18n::t;
+use std::path::Path;
 
 impl Editor {
-    /// Check if the file open dialog is active (for both OpenFile and SwitchProject)
+    /// Check if the file open dialog is active (for OpenFile, InsertFile and SwitchProject)
     pub fn is_file_open_active(&self) -> bool {
         self.prompt
             .as_ref()
             .map(|p| {
                 matches!(
                     p.prompt_type,
-                    PromptType::OpenFile | PromptType::SwitchProject
+                    PromptType::OpenFile | PromptType::InsertFile | PromptType::SwitchProject
                 )
             })
             .unwrap_or(false)
@@ -162,6 +164,12 @@ impl Editor {
             .map(|s| s.current_dir.clone())
             .unwrap_or_else(|| std::env::current_dir().unwrap_or_default());
 
+        // URLs such as ssh://host/path are opened through their provider
+        if !is_folder_mode && is_remote_path(Path::new(&prompt_input)) {
+            self.file_open_open_file(std::path::PathBuf::from(&prompt_input));
+            return;
+        }
+
         // If there's any prompt input, try to resolve it as a path
         if !prompt_input.is_empty() {
             let expanded_path = if prompt_input.starts_with('~') {
@@ -263,10 +271,20 @@ impl Editor {
 
     /// Open a file from the file browser
     fn file_open_open_file(&mut self, path: std::path::PathBuf) {
+        let inserting = self
+            .prompt
+            .as_ref()
+            .is_some_and(|p| p.prompt_type == PromptType::InsertFile);
+
         // Close the file browser
         self.file_open_state = None;
         self.prompt = None;
 
+        if inserting {
+            self.insert_file(path, false);
+            return;
+        }
+
         // Open the file
         if let Err(e) = self.open_file(&path) {
             self.set_status_message(t!("file.error_opening", error = e.to_string()).to_string());
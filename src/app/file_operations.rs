@@ -11,86 +11,71 @@
 use std::io;
 use std::path::{Path, PathBuf};
 
-use lsp_types::TextDocumentContentChangeEvent;
 use rust_i18n::t;
 
+use crate::model::encoding::TextEncoding;
 use crate::model::event::{BufferId, EventLog};
+use crate::services::file_provider::is_remote_path;
+use crate::services::lsp::document_sync::{whole_text_change, DocumentSync};
 use crate::services::lsp::manager::{detect_language, LspSpawnResult};
-use crate::state::EditorState;
 
 use super::{BufferMetadata, Editor};
 
 impl Editor {
     /// Save the active buffer
+    ///
+    /// See [`Editor::save_buffer`] for saves that take a while.
     pub fn save(&mut self) -> io::Result<()> {
-        let path = self
-            .active_state()
-            .buffer
-            .file_path()
-            .map(|p| p.to_path_buf());
-        self.active_state_mut().buffer.save()?;
-        self.status_message = Some(t!("status.file_saved").to_string());
-
-        // Mark the event log position as saved (for undo modified tracking)
-        self.active_event_log_mut().mark_saved();
-
-        // Update file modification time after save
-        if let Some(ref p) = path {
-            if let Ok(metadata) = std::fs::metadata(p) {
-                if let Ok(mtime) = metadata.modified() {
-                    self.file_mod_times.insert(p.clone(), mtime);
-                }
-            }
-        }
-
-        // Notify LSP of save
-        self.notify_lsp_save();
-
-        // Delete recovery file (buffer is now saved)
-        let _ = self.delete_buffer_recovery(self.active_buffer());
-
-        // Emit control event
-        if let Some(ref p) = path {
-            self.emit_event(
-                crate::model::control_event::events::FILE_SAVED.name,
-                serde_json::json!({
-                    "path": p.display().to_string()
-                }),
-            );
-        }
+        self.save_buffer(self.active_buffer())
+    }
 
-        // Fire AfterFileSave hook for plugins
-        if let Some(ref p) = path {
-            let buffer_id = self.active_buffer();
-            self.plugin_manager.run_hook(
-                "after_file_save",
-                crate::services::plugins::hooks::HookArgs::AfterFileSave {
-                    buffer_id,
-                    path: p.clone(),
-                },
-            );
+    /// Make a buffer follow the `eol` and `working-tree-encoding` attributes
+    /// `.gitattributes` gives `path`, before saving it there
+    ///
+    /// Returns a status note when an attribute changed how the file is
+    /// written, which only happens the first time after the buffer was
+    /// opened with other conventions.
+    pub(super) fn apply_gitattributes_for_save(
+        &mut self,
+        buffer_id: BufferId,
+        path: &Path,
+    ) -> Option<String> {
+        let attributes = self.git_attributes.lookup(path);
+        let buffer = &mut self.buffers.get_mut(&buffer_id)?.buffer;
+        let mut enforced = Vec::new();
+
+        if let Some(eol) = attributes.eol {
+            if buffer.line_ending() != eol {
+                buffer.set_line_ending(eol);
+                enforced.push(format!("eol={}", eol.display_name().to_lowercase()));
+            }
         }
 
-        // Run on-save actions (formatters, linters, etc.)
-        match self.run_on_save_actions() {
-            Ok(true) => {
-                // Actions ran successfully - if status_message was set by run_on_save_actions
-                // (e.g., for missing optional formatters), keep it. Otherwise update status.
-                if self.status_message.as_deref() == Some(&t!("status.file_saved")) {
-                    self.status_message = Some(t!("status.file_saved_with_actions").to_string());
+        if let Some(name) = attributes.working_tree_encoding {
+            if !buffer.is_binary() && !buffer.encoding().satisfies(&name) {
+                match TextEncoding::from_name(&name) {
+                    Some(encoding) if !buffer.is_large_file() => {
+                        buffer.set_encoding(encoding);
+                        enforced.push(format!("working-tree-encoding={}", name));
+                    }
+                    _ => {
+                        return Some(
+                            t!(
+                                "status.gitattributes_encoding_unsupported",
+                                encoding = name,
+                                current = buffer.encoding().display_name()
+                            )
+                            .to_string(),
+                        );
+                    }
                 }
-                // else: keep the message set by run_on_save_actions (e.g., missing formatter)
-            }
-            Ok(false) => {
-                // No actions configured, keep original status
-            }
-            Err(e) => {
-                // Action failed, show error but don't fail the save
-                self.status_message = Some(e);
             }
         }
 
-        Ok(())
+        if enforced.is_empty() {
+            return None;
+        }
+        Some(t!("status.gitattributes_enforced", attrs = enforced.join(", ")).to_string())
     }
 
     /// Revert the active buffer to the last saved version on disk
@@ -120,14 +105,7 @@ impl Editor {
         let old_cursors = self.active_state().cursors.clone();
 
         // Load the file content fresh from disk
-        let mut new_state = EditorState::from_file_with_languages(
-            &path,
-            self.terminal_width,
-            self.terminal_height,
-            self.config.editor.large_file_threshold_bytes as usize,
-            &self.grammar_registry,
-            &self.config.languages,
-        )?;
+        let mut new_state = self.reload_file_state(&path)?;
 
         // Restore cursor positions (clamped to valid range for new file size)
         let new_file_size = new_state.buffer.len();
@@ -171,6 +149,7 @@ impl Editor {
         // Notify LSP that the file was changed
         self.notify_lsp_file_changed(&path);
 
+        self.refresh_ansi_colors(buffer_id);
         self.status_message = Some(t!("status.reverted").to_string());
         Ok(true)
     }
@@ -191,11 +170,6 @@ impl Editor {
     /// Checks modification times of open files to detect external changes.
     /// Returns true if any file was changed (requires re-render).
     pub fn poll_file_changes(&mut self) -> bool {
-        // Skip if auto-revert is disabled
-        if !self.auto_revert_enabled {
-            return false;
-        }
-
         // Check poll interval
         let poll_interval =
             std::time::Duration::from_millis(self.config.editor.auto_revert_poll_interval_ms);
@@ -210,11 +184,19 @@ impl Editor {
         }
         self.last_auto_revert_poll = self.time_source.now();
 
-        // Collect paths of open files that need checking
+        // Attribute files edited since they were read are read again on next use
+        self.git_attributes.invalidate_stale();
+
+        // Collect paths of open files that need checking (auto-revert may be
+        // off globally but on for single buffers, and excluded paths are
+        // skipped). Files being saved change under us and are checked once
+        // the save has recorded their new modification time.
         let files_to_check: Vec<PathBuf> = self
             .buffers
-            .values()
-            .filter_map(|state| state.buffer.file_path().map(PathBuf::from))
+            .iter()
+            .filter(|(&buffer_id, _)| self.auto_revert_enabled_for(buffer_id))
+            .filter(|(buffer_id, _)| !self.file_saves.contains_key(buffer_id))
+            .filter_map(|(_, state)| state.buffer.file_path().map(PathBuf::from))
             .collect();
 
         let mut any_changed = false;
@@ -252,7 +234,8 @@ impl Editor {
     /// Poll for file tree changes (called from main loop)
     ///
     /// Checks modification times of expanded directories to detect new/deleted files.
-    /// Returns true if any directory was refreshed (requires re-render).
+    /// Changed directories are listed again with the explorer's next batch
+    /// of changes. Returns true if any entry count badge went stale (requires re-render).
     pub fn poll_file_tree_changes(&mut self) -> bool {
         // Check poll interval
         let poll_interval =
@@ -267,19 +250,18 @@ impl Editor {
             return false;
         };
 
-        // Collect expanded directories (node_id, path)
-        use crate::view::file_tree::NodeId;
-        let expanded_dirs: Vec<(NodeId, PathBuf)> = explorer
+        // Collect expanded directories
+        let expanded_dirs: Vec<PathBuf> = explorer
             .tree()
             .all_nodes()
             .filter(|node| node.is_dir() && node.is_expanded())
-            .map(|node| (node.id, node.entry.path.clone()))
+            .map(|node| node.entry.path.clone())
             .collect();
 
         // Check mtimes and collect directories that need refresh
-        let mut dirs_to_refresh: Vec<NodeId> = Vec::new();
+        let mut dirs_to_refresh: Vec<PathBuf> = Vec::new();
 
-        for (node_id, path) in expanded_dirs {
+        for path in expanded_dirs {
             // Get current mtime
             let current_mtime = match std::fs::metadata(&path) {
                 Ok(meta) => match meta.modified() {
@@ -294,8 +276,8 @@ impl Editor {
                 if current_mtime != stored_mtime {
                     // Update stored mtime
                     self.dir_mod_times.insert(path.clone(), current_mtime);
-                    dirs_to_refresh.push(node_id);
                     tracing::debug!("Directory changed: {:?}", path);
+                    dirs_to_refresh.push(path);
                 }
             } else {
                 // First time seeing this directory, record its mtime
@@ -303,22 +285,30 @@ impl Editor {
             }
         }
 
-        // Refresh changed directories
-        if dirs_to_refresh.is_empty() {
-            return false;
-        }
-
-        // Refresh each changed directory
-        if let (Some(runtime), Some(explorer)) = (&self.tokio_runtime, &mut self.file_explorer) {
-            for node_id in dirs_to_refresh {
-                let tree = explorer.tree_mut();
-                if let Err(e) = runtime.block_on(tree.refresh_node(node_id)) {
-                    tracing::warn!("Failed to refresh directory: {}", e);
+        // Entry count badges go stale when their directory changes
+        let stale_counts: Vec<PathBuf> = explorer
+            .cached_entry_counts()
+            .filter(|(path, counted_mtime)| {
+                std::fs::metadata(path)
+                    .and_then(|meta| meta.modified())
+                    .ok()
+                    != *counted_mtime
+            })
+            .map(|(path, _)| path.clone())
+            .collect();
+        if !stale_counts.is_empty() {
+            if let Some(explorer) = &mut self.file_explorer {
+                for path in &stale_counts {
+                    explorer.invalidate_entry_count(path);
                 }
             }
         }
 
-        true
+        for path in dirs_to_refresh {
+            self.queue_file_explorer_rescan(path);
+        }
+
+        !stale_counts.is_empty()
     }
 
     /// Notify LSP server about a newly opened file
@@ -370,7 +360,6 @@ impl Editor {
         };
 
         let enable_inlay_hints = self.config.editor.enable_inlay_hints;
-        let previous_result_id = self.diagnostic_result_ids.get(uri.as_str()).cloned();
 
         // Get buffer line count for inlay hints
         let (last_line, last_char) = self
@@ -399,12 +388,13 @@ impl Editor {
 
         match lsp.try_spawn(&language) {
             LspSpawnResult::Spawned => {
-                if let Some(client) = lsp.get_or_spawn(&language) {
-                    // Send didOpen
-                    tracing::info!("Sending didOpen to LSP for: {}", uri.as_str());
-                    if let Err(e) = client.did_open(uri.clone(), text, language.clone()) {
+                let servers = lsp.get_or_spawn_all(&language);
+                // Every server of the language gets the document
+                for (label, client) in &servers {
+                    tracing::info!("Sending didOpen to LSP ({}) for: {}", label, uri.as_str());
+                    if let Err(e) = client.did_open(uri.clone(), text.clone(), language.clone()) {
                         tracing::warn!("Failed to send didOpen to LSP: {}", e);
-                        return;
+                        continue;
                     }
                     tracing::info!("Successfully sent didOpen to LSP");
 
@@ -412,6 +402,10 @@ impl Editor {
                     metadata.lsp_opened_with.insert(client.id());
 
                     // Request pull diagnostics
+                    let previous_result_id = self
+                        .diagnostic_result_ids
+                        .get(&(label.to_string(), uri.as_str().to_string()))
+                        .cloned();
                     let request_id = self.next_lsp_request_id;
                     self.next_lsp_request_id += 1;
                     if let Err(e) =
@@ -428,7 +422,10 @@ impl Editor {
                             request_id
                         );
                     }
+                }
 
+                // Inlay hints come from the primary server only
+                if let Some((_, client)) = servers.first() {
                     // Request inlay hints
                     if enable_inlay_hints {
                         let request_id = self.next_lsp_request_id;
@@ -468,6 +465,10 @@ impl Editor {
     /// Record a file's modification time (called when opening files)
     /// This is used by the polling-based auto-revert to detect external changes
     pub(crate) fn watch_file(&mut self, path: &Path) {
+        // Remote files aren't checked for changes
+        if is_remote_path(path) {
+            return;
+        }
         // Record current modification time for polling
         if let Ok(metadata) = std::fs::metadata(path) {
             if let Ok(mtime) = metadata.modified() {
@@ -478,16 +479,6 @@ impl Editor {
 
     /// Notify LSP that a file's contents changed (e.g., after revert)
     pub(crate) fn notify_lsp_file_changed(&mut self, path: &Path) {
-        let Ok(uri) = url::Url::from_file_path(path) else {
-            return;
-        };
-        let Ok(lsp_uri) = uri.as_str().parse::<lsp_types::Uri>() else {
-            return;
-        };
-        let Some(language) = detect_language(path, &self.config.languages) else {
-            return;
-        };
-
         // Find the buffer ID for this path
         let Some((buffer_id, content)) = self
             .buffers
@@ -498,62 +489,18 @@ impl Editor {
             return;
         };
 
-        // Get handle ID
-        let handle_id = {
-            let Some(lsp) = self.lsp.as_mut() else {
-                return;
-            };
-            let Some(handle) = lsp.get_or_spawn(&language) else {
-                return;
-            };
-            handle.id()
-        };
-
-        // Check if didOpen needs to be sent first
-        let needs_open = {
-            let Some(metadata) = self.buffer_metadata.get(&buffer_id) else {
-                return;
-            };
-            !metadata.lsp_opened_with.contains(&handle_id)
-        };
-
-        if needs_open {
-            // Send didOpen first
-            if let Some(lsp) = self.lsp.as_mut() {
-                if let Some(handle) = lsp.get_or_spawn(&language) {
-                    if let Err(e) =
-                        handle.did_open(lsp_uri.clone(), content.clone(), language.clone())
-                    {
-                        tracing::warn!("Failed to send didOpen before didChange: {}", e);
-                        return;
-                    }
-                    tracing::debug!(
-                        "Sent didOpen for {} to LSP handle {} before file change notification",
-                        lsp_uri.as_str(),
-                        handle_id
-                    );
-                }
-            }
-
-            // Mark as opened
-            if let Some(metadata) = self.buffer_metadata.get_mut(&buffer_id) {
-                metadata.lsp_opened_with.insert(handle_id);
-            }
-        }
-
-        // Use full document sync - send the entire new content
-        if let Some(lsp) = &mut self.lsp {
-            if let Some(client) = lsp.get_or_spawn(&language) {
-                let content_change = TextDocumentContentChangeEvent {
-                    range: None, // None means full document replacement
-                    range_length: None,
-                    text: content,
-                };
-                if let Err(e) = client.did_change(lsp_uri, vec![content_change]) {
-                    tracing::warn!("Failed to notify LSP of file change: {}", e);
-                }
-            }
-        }
+        // Use full document sync - send each server the entire new content,
+        // which makes any left due to a full-sync server stale. A server that
+        // doesn't have the buffer yet gets didOpen instead.
+        self.cancel_lsp_sync(buffer_id);
+        let servers: Vec<String> = self
+            .buffer_lsp_servers(buffer_id)
+            .into_iter()
+            .filter(|label| {
+                self.lsp_server_sync(label).map(|sync| sync.sync) != Some(DocumentSync::None)
+            })
+            .collect();
+        self.send_lsp_changes_for_buffer(buffer_id, vec![whole_text_change(content)], &servers);
     }
 
     /// Revert a specific buffer by ID without affecting the active viewport.
@@ -563,14 +510,7 @@ impl Editor {
     /// cursors (clamped to valid positions), but does NOT touch any viewport state.
     fn revert_buffer_by_id(&mut self, buffer_id: BufferId, path: &Path) -> io::Result<()> {
         // Load the file content fresh from disk
-        let new_state = EditorState::from_file_with_languages(
-            path,
-            self.terminal_width,
-            self.terminal_height,
-            self.config.editor.large_file_threshold_bytes as usize,
-            &self.grammar_registry,
-            &self.config.languages,
-        )?;
+        let new_state = self.reload_file_state(path)?;
 
         // Get the new file size for clamping
         let new_file_size = new_state.buffer.len();
@@ -613,6 +553,7 @@ impl Editor {
         // Notify LSP that the file was changed
         self.notify_lsp_file_changed(path);
 
+        self.refresh_ansi_colors(buffer_id);
         Ok(())
     }
 
@@ -633,6 +574,14 @@ impl Editor {
         }
 
         for buffer_id in buffer_ids {
+            // A change of permissions comes in as a change too
+            self.refresh_file_writability(buffer_id);
+
+            if !self.auto_revert_enabled_for(buffer_id) || self.file_saves.contains_key(&buffer_id)
+            {
+                continue;
+            }
+
             let state = match self.buffers.get(&buffer_id) {
                 Some(s) => s,
                 None => continue,
@@ -665,45 +614,60 @@ impl Editor {
                 continue;
             }
 
-            // Auto-revert if enabled and buffer is not modified
-            if self.auto_revert_enabled {
-                // Optimistic concurrency: re-check mtime before reverting.
-                // A save may have completed between our first check and now,
-                // updating file_mod_times. If so, skip the revert.
-                let still_needs_revert = self
-                    .file_mod_times
-                    .get(&path)
-                    .map(|stored| current_mtime > *stored)
-                    .unwrap_or(true);
-
-                if !still_needs_revert {
-                    continue;
-                }
+            // Optimistic concurrency: re-check mtime before reverting.
+            // A save may have completed between our first check and now,
+            // updating file_mod_times. If so, skip the revert.
+            let still_needs_revert = self
+                .file_mod_times
+                .get(&path)
+                .map(|stored| current_mtime > *stored)
+                .unwrap_or(true);
 
-                // Check if this buffer is currently displayed in the active split
-                let is_active_buffer = buffer_id == self.active_buffer();
+            if !still_needs_revert {
+                continue;
+            }
 
-                if is_active_buffer {
-                    // Use revert_file() which preserves viewport for active buffer
-                    if let Err(e) = self.revert_file() {
-                        tracing::error!("Failed to auto-revert file {:?}: {}", path, e);
-                    } else {
-                        tracing::info!("Auto-reverted file: {:?}", path);
-                    }
-                } else {
-                    // Use revert_buffer_by_id() which doesn't touch any viewport
-                    // This prevents corrupting the active split's viewport state
-                    if let Err(e) = self.revert_buffer_by_id(buffer_id, &path) {
-                        tracing::error!("Failed to auto-revert background file {:?}: {}", path, e);
-                    } else {
-                        tracing::info!("Auto-reverted file: {:?}", path);
-                    }
-                }
+            if self.should_ask_before_revert(buffer_id) {
+                self.ask_to_reload(buffer_id);
+                continue;
+            }
 
-                // Update the modification time tracking for this file
-                self.watch_file(&path);
+            self.reload_changed_buffer(buffer_id, &path);
+        }
+    }
+
+    /// Reload a buffer whose file changed on disk
+    pub(super) fn reload_changed_buffer(&mut self, buffer_id: BufferId, path: &Path) {
+        // Check if this buffer is currently displayed in the active split
+        let is_active_buffer = buffer_id == self.active_buffer();
+
+        if is_active_buffer {
+            // Use revert_file() which preserves viewport for active buffer
+            if let Err(e) = self.revert_file() {
+                tracing::error!("Failed to auto-revert file {:?}: {}", path, e);
+            } else {
+                tracing::info!("Auto-reverted file: {:?}", path);
+            }
+        } else {
+            // Use revert_buffer_by_id() which doesn't touch any viewport
+            // This prevents corrupting the active split's viewport state
+            if let Err(e) = self.revert_buffer_by_id(buffer_id, path) {
+                tracing::error!("Failed to auto-revert background file {:?}: {}", path, e);
+            } else {
+                tracing::info!("Auto-reverted file: {:?}", path);
             }
         }
+
+        // Update the modification time tracking for this file
+        self.watch_file(path);
+
+        if self
+            .buffer_metadata
+            .get(&buffer_id)
+            .is_some_and(|m| m.follow_tail)
+        {
+            self.pin_follow_tail(buffer_id);
+        }
     }
 
     /// Check if saving would overwrite changes made by another process
@@ -0,0 +1,114 @@
+//! Buffers whose files can't be written
+//!
+//! Whether a file can be written is checked when it is opened, when it
+//! changes on disk and before it is saved. A buffer whose file can't be
+//! written is read-only and shows "[RO]" in the status bar, until "Edit
+//! Anyway" unlocks it; saving it then fails, and the failure offers "Save
+//! As…" and, with `files.sudo_save_command` set, writing the file through
+//! that command (see [`Editor::report_failed_save`]).
+
+use rust_i18n::t;
+
+use super::{BufferMetadata, Editor};
+use crate::model::event::BufferId;
+use crate::services::file_access::{read_only_reason, ReadOnlyReason};
+use crate::services::file_provider::is_remote_path;
+use crate::view::notifications::NotificationSeverity;
+
+impl Editor {
+    /// Check whether a buffer's file can be written, and lock or unlock the
+    /// buffer when that changed
+    ///
+    /// A buffer that becomes read-only says why in a notification once.
+    pub(super) fn refresh_file_writability(&mut self, buffer_id: BufferId) {
+        let Some(metadata) = self.buffer_metadata.get(&buffer_id) else {
+            return;
+        };
+        // Binary buffers are read-only anyway
+        if metadata.binary {
+            return;
+        }
+        let Some(path) = metadata
+            .file_path()
+            .filter(|path| !path.as_os_str().is_empty() && !is_remote_path(path))
+        else {
+            return;
+        };
+        let reason = read_only_reason(path);
+        if reason == metadata.fs_read_only {
+            return;
+        }
+
+        let was_locked = metadata.fs_read_only.is_some() && !metadata.edit_anyway;
+        let newly_read_only = metadata.fs_read_only.is_none();
+        let name = metadata.display_name.clone();
+        self.update_file_lock(buffer_id, |metadata| {
+            metadata.fs_read_only = reason;
+            if reason.is_none() {
+                metadata.edit_anyway = false;
+            }
+        });
+
+        match reason {
+            Some(reason) if newly_read_only => {
+                tracing::info!("{} can't be written: {:?}", name, reason);
+                let message = match reason {
+                    ReadOnlyReason::Permissions => t!("file.read_only_permissions", name = name),
+                    ReadOnlyReason::ReadOnlyFilesystem => {
+                        t!("file.read_only_filesystem", name = name)
+                    }
+                };
+                self.notify(NotificationSeverity::Warning, message.to_string());
+            }
+            None if was_locked => {
+                self.set_status_message(t!("file.writable_again", name = name).to_string());
+            }
+            _ => {}
+        }
+    }
+
+    /// Whether the active buffer is locked because its file can't be written
+    pub fn is_active_file_read_only(&self) -> bool {
+        self.buffer_metadata
+            .get(&self.active_buffer())
+            .is_some_and(|metadata| metadata.fs_read_only.is_some() && !metadata.edit_anyway)
+    }
+
+    /// Unlock the active buffer for editing although its file can't be written
+    pub fn edit_anyway(&mut self) {
+        let buffer_id = self.active_buffer();
+        let Some(metadata) = self.buffer_metadata.get(&buffer_id) else {
+            return;
+        };
+        let name = metadata.display_name.clone();
+        if metadata.fs_read_only.is_none() {
+            self.set_status_message(t!("file.not_read_only", name = name).to_string());
+            return;
+        }
+        self.update_file_lock(buffer_id, |metadata| metadata.edit_anyway = true);
+        self.set_status_message(t!("file.edit_anyway", name = name).to_string());
+    }
+
+    /// Change what keeps a buffer's file locked, and lock or unlock the
+    /// buffer when that changes whether it is
+    ///
+    /// See [`BufferMetadata::file_locked`].
+    pub(super) fn update_file_lock(
+        &mut self,
+        buffer_id: BufferId,
+        update: impl FnOnce(&mut BufferMetadata),
+    ) {
+        let Some(metadata) = self.buffer_metadata.get_mut(&buffer_id) else {
+            return;
+        };
+        let was_locked = metadata.file_locked();
+        update(metadata);
+        let locked = metadata.file_locked();
+        if locked != was_locked {
+            metadata.read_only = locked;
+            if let Some(state) = self.buffers.get_mut(&buffer_id) {
+                state.editing_disabled = locked;
+            }
+        }
+    }
+}
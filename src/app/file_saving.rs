@@ -0,0 +1,563 @@
+//! Background file saving for the Editor.
+//!
+//! Saving writes a snapshot of the buffer on the worker pool. Saves that
+//! finish within [`QUICK_SAVE_TIMEOUT`] behave exactly like a synchronous
+//! save; slower ones (huge buffers, slow disks) continue in the background
+//! while the buffer stays editable, and are completed by
+//! [`Editor::poll_file_saves`]. Background saves show as progress entries.
+//! The buffer only counts as saved once the write succeeded, and edits made
+//! meanwhile leave it modified.
+
+use std::io;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+use rust_i18n::t;
+
+use crate::input::keybindings::Action;
+use crate::model::event::BufferId;
+use crate::services::file_access::reason_for_error;
+use crate::services::file_provider::is_remote_path;
+use crate::services::file_saver::{CommandFileSink, FileSave, FileSink};
+use crate::view::notifications::{
+    next_notification_id, Notification, NotificationAction, NotificationSeverity,
+};
+use crate::view::prompt::PromptType;
+
+use super::file_loading::display_file_name;
+use super::Editor;
+
+/// How long saving waits for the write before continuing it in the background
+pub(crate) const QUICK_SAVE_TIMEOUT: Duration = Duration::from_millis(100);
+
+/// How long quitting waits for background saves before asking what to do
+pub(crate) const QUIT_SAVE_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Action id of the retry button on a failed-save notification
+const RETRY_ACTION: &str = "retry";
+
+/// Action id of the "Save As…" button on a failed-save notification
+const SAVE_AS_ACTION: &str = "save_as";
+
+/// Action id of the "Retry with sudo" button on a failed-save notification
+const SUDO_ACTION: &str = "sudo";
+
+/// Action id of the "Save Local Copy" button on a failed save of a remote file
+const LOCAL_COPY_ACTION: &str = "local_copy";
+
+/// Bookkeeping for a buffer whose file is being written
+pub(crate) struct PendingSave {
+    save: FileSave,
+    /// Event log position of the snapshot being written
+    log_index: usize,
+    /// Status note from applying `.gitattributes`, shown once saved
+    note: Option<String>,
+    /// Another save was requested meanwhile; it runs once this one ends
+    queued: bool,
+    /// Last progress percentage reported
+    shown_percent: Option<u8>,
+    /// Progress entry shown while it runs in the background
+    progress_id: Option<u32>,
+}
+
+impl Editor {
+    /// Replace where file contents are written (used by tests to simulate slow disks)
+    pub fn set_file_sink(&mut self, sink: Arc<dyn FileSink>) {
+        self.file_sink = sink;
+    }
+
+    /// Set how long saving blocks before continuing the write in the background
+    pub fn set_quick_save_timeout(&mut self, timeout: Duration) {
+        self.quick_save_timeout = timeout;
+    }
+
+    /// Whether a buffer's file is being written in the background
+    pub fn is_buffer_saving(&self, buffer_id: BufferId) -> bool {
+        self.file_saves.contains_key(&buffer_id)
+    }
+
+    /// Save a buffer to its file
+    ///
+    /// Returns once the file is written, or after the quick-save window with
+    /// the write continuing in the background. A save requested while one
+    /// is running is queued, and then writes the contents as they are when
+    /// the running one ends.
+    pub fn save_buffer(&mut self, buffer_id: BufferId) -> io::Result<()> {
+        // Remote files are written through their provider
+        let path = self
+            .buffers
+            .get(&buffer_id)
+            .and_then(|state| state.buffer.file_path());
+        let sink = match path
+            .map(|path| self.remote_file_provider(path))
+            .transpose()?
+        {
+            Some(Some(provider)) => provider as Arc<dyn FileSink>,
+            _ => Arc::clone(&self.file_sink),
+        };
+        self.save_buffer_to(buffer_id, sink)
+    }
+
+    /// Save a buffer to its file, writing it through `sink`
+    fn save_buffer_to(&mut self, buffer_id: BufferId, sink: Arc<dyn FileSink>) -> io::Result<()> {
+        if self.file_loads.contains_key(&buffer_id) {
+            return Err(io::Error::other(t!("buffer.still_loading").to_string()));
+        }
+        // The buffer holds the file without its escape sequences; the file
+        // itself is unchanged
+        if self.is_showing_ansi_colors(buffer_id) {
+            self.set_status_message(t!("ansi.save_blocked").to_string());
+            return Ok(());
+        }
+        if self.protected_save_refused(buffer_id) {
+            let name = self
+                .buffer_metadata
+                .get(&buffer_id)
+                .map(|metadata| metadata.display_name.clone())
+                .unwrap_or_default();
+            return Err(io::Error::other(
+                t!("protected.save_unconfirmed", name = name).to_string(),
+            ));
+        }
+        if let Some(pending) = self.file_saves.get_mut(&buffer_id) {
+            pending.queued = true;
+            let name = display_file_name(pending.save.path());
+            self.set_status_message(t!("file.save_queued", name = name).to_string());
+            return Ok(());
+        }
+
+        let path = self
+            .buffers
+            .get(&buffer_id)
+            .and_then(|state| state.buffer.file_path())
+            .map(|p| p.to_path_buf())
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::NotFound,
+                    "No file path associated with buffer",
+                )
+            })?;
+        let note = if is_remote_path(&path) {
+            None
+        } else {
+            self.apply_gitattributes_for_save(buffer_id, &path)
+        };
+        let snapshot = match self.buffers.get(&buffer_id) {
+            Some(state) => state.buffer.save_snapshot()?,
+            None => return Ok(()),
+        };
+        let log_index = self
+            .event_logs
+            .get(&buffer_id)
+            .map_or(0, |log| log.current_index());
+
+        let wake = self.async_bridge.as_ref().map(|bridge| bridge.sender());
+        let save = FileSave::start(&self.worker_pool, sink, &path, snapshot, wake);
+        let mut pending = PendingSave {
+            save,
+            log_index,
+            note,
+            queued: false,
+            shown_percent: None,
+            progress_id: None,
+        };
+        match pending.save.wait(self.quick_save_timeout) {
+            Some(Ok(file_size)) => {
+                self.complete_save(buffer_id, pending, file_size, false);
+                Ok(())
+            }
+            Some(Err(e)) => Err(e),
+            None => {
+                tracing::info!("Saving {} in the background", path.display());
+                let percent = pending.save.percent();
+                let progress_id = self.start_progress(
+                    t!("file.saving_title", name = display_file_name(&path)).to_string(),
+                );
+                self.update_progress(progress_id, Some(percent), None);
+                pending.shown_percent = Some(percent);
+                pending.progress_id = Some(progress_id);
+                self.file_saves.insert(buffer_id, pending);
+                Ok(())
+            }
+        }
+    }
+
+    /// Collect progress from background saves and complete finished ones
+    ///
+    /// Returns true if anything visible changed.
+    pub(super) fn poll_file_saves(&mut self) -> bool {
+        self.poll_recovery_writes();
+        if self.file_saves.is_empty() && self.quit_after_saves.is_none() {
+            return false;
+        }
+
+        let mut finished = Vec::new();
+        let mut progress = Vec::new();
+
+        for (buffer_id, pending) in self.file_saves.iter_mut() {
+            if let Some(result) = pending.save.poll() {
+                finished.push((*buffer_id, result));
+                continue;
+            }
+            let percent = pending.save.percent();
+            if pending.shown_percent != Some(percent) {
+                pending.shown_percent = Some(percent);
+                progress.extend(pending.progress_id.map(|id| (id, percent)));
+            }
+        }
+
+        let mut changed = !progress.is_empty() || !finished.is_empty();
+        for (progress_id, percent) in progress {
+            self.update_progress(progress_id, Some(percent), None);
+        }
+        for (buffer_id, result) in finished {
+            let Some(pending) = self.file_saves.remove(&buffer_id) else {
+                continue;
+            };
+            if let Some(progress_id) = pending.progress_id {
+                self.finish_progress(progress_id);
+            }
+            match result {
+                Ok(file_size) => self.complete_save(buffer_id, pending, file_size, true),
+                Err(e) => self.fail_background_save(buffer_id, &pending, e),
+            }
+        }
+        if self.quit_after_saves.is_some() {
+            changed |= self.check_quit_after_saves();
+        }
+        changed
+    }
+
+    /// Make a written snapshot the buffer's saved state and run what follows
+    /// a save
+    ///
+    /// `in_background` is set for saves that outlived the quick-save window,
+    /// which name the file in the status bar since the user may have moved on.
+    fn complete_save(
+        &mut self,
+        buffer_id: BufferId,
+        pending: PendingSave,
+        file_size: u64,
+        in_background: bool,
+    ) {
+        let PendingSave {
+            save,
+            log_index,
+            note,
+            queued,
+            ..
+        } = pending;
+        let path = save.path().to_path_buf();
+
+        // Update file modification time after save
+        if let Ok(mtime) = std::fs::metadata(&path).and_then(|m| m.modified()) {
+            self.file_mod_times.insert(path.clone(), mtime);
+        }
+
+        let Some(state) = self.buffers.get_mut(&buffer_id) else {
+            tracing::info!("Saved {} after its buffer was closed", path.display());
+            return;
+        };
+        state
+            .buffer
+            .finish_save(save.snapshot(), &path, file_size as usize);
+        let edited_meanwhile = state.buffer.is_modified();
+
+        let saved_message = if in_background {
+            t!("status.file_saved_name", name = display_file_name(&path)).to_string()
+        } else {
+            t!("status.file_saved").to_string()
+        };
+        self.status_message = Some(note.unwrap_or(saved_message));
+
+        // Mark the event log position as saved (for undo modified tracking)
+        if let Some(log) = self.event_logs.get_mut(&buffer_id) {
+            log.mark_saved_at(log_index);
+        }
+
+        // Notify LSP of save
+        self.notify_lsp_save(buffer_id);
+
+        // Delete recovery file (buffer is now saved); edits made during the
+        // save still need one
+        let _ = self.delete_buffer_recovery(buffer_id);
+        if edited_meanwhile {
+            if let Some(state) = self.buffers.get_mut(&buffer_id) {
+                state.buffer.set_recovery_pending(true);
+            }
+        }
+
+        // Emit control event
+        self.emit_event(
+            crate::model::control_event::events::FILE_SAVED.name,
+            serde_json::json!({
+                "path": path.display().to_string()
+            }),
+        );
+
+        // Fire AfterFileSave hook for plugins
+        self.plugin_manager.run_hook(
+            "after_file_save",
+            crate::services::plugins::hooks::HookArgs::AfterFileSave {
+                buffer_id,
+                path: path.clone(),
+            },
+        );
+
+        // On-save actions (linters, etc.) work on the active buffer
+        if buffer_id == self.active_buffer() {
+            self.run_on_save_actions_after_save();
+        } else {
+            tracing::info!(
+                "Skipping on-save actions for {}: no longer the active buffer",
+                path.display()
+            );
+        }
+
+        if self.format_after_save.remove(&buffer_id) {
+            self.format_saved_file(buffer_id);
+        }
+
+        if queued {
+            self.retry_save(buffer_id);
+        }
+    }
+
+    /// Run on-save actions for the active buffer, reporting in the status bar
+    fn run_on_save_actions_after_save(&mut self) {
+        match self.run_on_save_actions() {
+            Ok(true) => {
+                // Actions ran successfully - if status_message was set by run_on_save_actions
+                // (e.g., for missing optional formatters), keep it. Otherwise update status.
+                if self.status_message.as_deref() == Some(&t!("status.file_saved")) {
+                    self.status_message = Some(t!("status.file_saved_with_actions").to_string());
+                }
+                // else: keep the message set by run_on_save_actions (e.g., missing formatter)
+            }
+            Ok(false) => {
+                // No actions configured, keep original status
+            }
+            Err(e) => {
+                // Action failed, show error but don't fail the save
+                self.status_message = Some(e);
+            }
+        }
+    }
+
+    /// Report a background save that failed
+    fn fail_background_save(
+        &mut self,
+        buffer_id: BufferId,
+        pending: &PendingSave,
+        error: io::Error,
+    ) {
+        let path = pending.save.path().to_path_buf();
+        self.format_after_save.remove(&buffer_id);
+        self.report_failed_save(buffer_id, &path, error);
+    }
+
+    /// Report a save that failed, offering to retry it
+    ///
+    /// A save that failed for lack of permission also offers "Save As…"
+    /// and, with `files.sudo_save_command` set, "Retry with sudo"; one of a
+    /// remote file offers to save a local copy. The buffer keeps its
+    /// modified flag; its file is left as it was.
+    pub(super) fn report_failed_save(
+        &mut self,
+        buffer_id: BufferId,
+        path: &Path,
+        error: io::Error,
+    ) {
+        tracing::error!("Failed to save {}: {}", path.display(), error);
+        let message = t!(
+            "file.save_failed_name",
+            name = display_file_name(path),
+            error = error.to_string()
+        )
+        .to_string();
+        self.set_status_message(message.clone());
+
+        let mut actions = vec![NotificationAction {
+            id: RETRY_ACTION.to_string(),
+            label: t!("file.save_retry").to_string(),
+        }];
+        if is_remote_path(path) {
+            actions.push(NotificationAction {
+                id: LOCAL_COPY_ACTION.to_string(),
+                label: t!("remote.save_local_copy").to_string(),
+            });
+        } else if reason_for_error(&error).is_some() {
+            actions.push(NotificationAction {
+                id: SAVE_AS_ACTION.to_string(),
+                label: t!("file.save_as_instead").to_string(),
+            });
+            if self.sudo_save_available() {
+                actions.push(NotificationAction {
+                    id: SUDO_ACTION.to_string(),
+                    label: t!("file.save_retry_sudo").to_string(),
+                });
+            }
+        }
+
+        let id = next_notification_id();
+        self.post_notification(
+            Notification::new(id.clone(), NotificationSeverity::Error, message)
+                .with_actions(actions)
+                .with_timeout(None),
+        );
+        self.save_retries.insert(id, buffer_id);
+    }
+
+    /// Handle the answer to a failed-save notification
+    ///
+    /// Returns false if the notification wasn't about a failed save.
+    pub(super) fn handle_save_retry_answer(
+        &mut self,
+        notification_id: &str,
+        action: Option<&str>,
+    ) -> bool {
+        let Some(buffer_id) = self.save_retries.remove(notification_id) else {
+            return false;
+        };
+        match action {
+            Some(RETRY_ACTION) => self.retry_save(buffer_id),
+            Some(SAVE_AS_ACTION) if self.buffers.contains_key(&buffer_id) => {
+                self.set_active_buffer(buffer_id);
+                if let Err(e) = self.handle_action(Action::SaveAs) {
+                    tracing::error!("Failed to start Save As: {}", e);
+                }
+            }
+            Some(SUDO_ACTION) => self.confirm_sudo_save(buffer_id),
+            Some(LOCAL_COPY_ACTION) => self.save_remote_local_copy(buffer_id),
+            _ => {}
+        }
+        true
+    }
+
+    /// Whether a failed save can be retried with `files.sudo_save_command`
+    fn sudo_save_available(&self) -> bool {
+        cfg!(unix) && !self.config.files.sudo_save_command.is_empty()
+    }
+
+    /// Ask before writing a buffer's file with `files.sudo_save_command`
+    fn confirm_sudo_save(&mut self, buffer_id: BufferId) {
+        let Some(path) = self
+            .buffers
+            .get(&buffer_id)
+            .and_then(|state| state.buffer.file_path())
+            .map(Path::to_path_buf)
+        else {
+            return;
+        };
+        self.start_prompt(
+            t!(
+                "file.sudo_save_confirm",
+                name = display_file_name(&path),
+                command = self.config.files.sudo_save_command.join(" ")
+            )
+            .to_string(),
+            PromptType::ConfirmSudoSave { buffer_id },
+        );
+    }
+
+    /// Answer to "write the file with `files.sudo_save_command`?"
+    pub(super) fn handle_confirm_sudo_save(&mut self, input: &str, buffer_id: BufferId) {
+        let input_lower = input.trim().to_lowercase();
+        if input_lower != "y" && input_lower != "yes" {
+            self.set_status_message(t!("buffer.save_cancelled").to_string());
+            return;
+        }
+        if !self.sudo_save_available() || !self.buffers.contains_key(&buffer_id) {
+            return;
+        }
+        let sink = Arc::new(CommandFileSink::new(
+            self.config.files.sudo_save_command.clone(),
+        ));
+        if let Err(e) = self.save_buffer_to(buffer_id, sink) {
+            let path = self
+                .buffers
+                .get(&buffer_id)
+                .and_then(|state| state.buffer.file_path())
+                .map(Path::to_path_buf)
+                .unwrap_or_default();
+            self.report_failed_save(buffer_id, &path, e);
+        }
+    }
+
+    /// Save a buffer again, if it is still open
+    fn retry_save(&mut self, buffer_id: BufferId) {
+        if !self.buffers.contains_key(&buffer_id) {
+            return;
+        }
+        if let Err(e) = self.save_buffer(buffer_id) {
+            self.set_status_message(t!("file.save_failed", error = e.to_string()).to_string());
+        }
+    }
+
+    /// Hold off quitting until background saves end
+    ///
+    /// Quitting continues once they have; after [`QUIT_SAVE_TIMEOUT`] the
+    /// user is asked whether to quit without them.
+    pub(super) fn wait_for_saves_then_quit(&mut self) {
+        if self.quit_after_saves.is_none() {
+            self.quit_after_saves = Some(self.time_source.now() + QUIT_SAVE_TIMEOUT);
+        }
+        self.show_quit_wait_progress();
+    }
+
+    /// Continue a quit that is waiting for background saves
+    ///
+    /// Returns true if anything visible changed.
+    fn check_quit_after_saves(&mut self) -> bool {
+        let Some(deadline) = self.quit_after_saves else {
+            return false;
+        };
+        if self.file_saves.is_empty() {
+            self.quit_after_saves = None;
+            self.quit();
+            return true;
+        }
+        if self.time_source.now() < deadline {
+            return self.show_quit_wait_progress();
+        }
+
+        self.quit_after_saves = None;
+        let discard_key = t!("prompt.key.discard").to_string();
+        let cancel_key = t!("prompt.key.cancel").to_string();
+        self.start_prompt(
+            t!(
+                "prompt.quit_saves_running",
+                count = self.file_saves.len(),
+                discard_key = discard_key,
+                cancel_key = cancel_key
+            )
+            .to_string(),
+            PromptType::ConfirmQuitWithSavesRunning,
+        );
+        true
+    }
+
+    /// Show how far the saves holding up quitting are
+    ///
+    /// Returns true if the status bar changed.
+    fn show_quit_wait_progress(&mut self) -> bool {
+        let percent = self
+            .file_saves
+            .values()
+            .map(|pending| pending.save.percent())
+            .min()
+            .unwrap_or(100);
+        let message = t!(
+            "file.quit_waiting_for_saves",
+            count = self.file_saves.len(),
+            percent = percent
+        )
+        .to_string();
+        if self.status_message.as_deref() == Some(message.as_str()) {
+            return false;
+        }
+        self.set_status_message(message);
+        true
+    }
+}
@@ -0,0 +1,464 @@
+//! Formatting buffers with their language's external formatter
+//!
+//! **Format Buffer** and format-on-save run the `formatter` of the buffer's
+//! language on the worker pool (see [`crate::services::formatter`]).
+//! Formatters that finish within [`QUICK_FORMAT_TIMEOUT`] behave as if they
+//! ran synchronously; slower ones show a progress entry and are finished by
+//! [`Editor::poll_formats`].
+//!
+//! Formatted text replaces only the lines that changed, as one undo step,
+//! so cursors, markers and overlays on other lines stay where they were. A
+//! result that arrives after the buffer was edited, or while another buffer
+//! is active, is dropped.
+//!
+//! Saving with `format_on_save` formats the text of a stdin-mode formatter
+//! first and saves the result; when formatting fails, the formatter's
+//! `on_failure` decides whether the unformatted text is saved anyway. A
+//! file-mode formatter runs on the saved file instead, which is then
+//! reloaded like **Revert Buffer to Saved**. Only saves the user asks for
+//! are formatted: recovery writes, retries and "save and close" aren't.
+
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use rust_i18n::t;
+
+use super::file_loading::display_file_name;
+use super::Editor;
+use crate::config::{FormatFailure, FormatterConfig};
+use crate::model::event::BufferId;
+use crate::services::command_trust::CommandRequest;
+use crate::services::file_provider::is_remote_path;
+use crate::services::formatter::{FormatCommand, FormatError, FormatInput, FormatJob};
+use crate::services::lsp::manager::detect_language;
+use crate::view::notifications::NotificationSeverity;
+
+/// How long formatting waits for the formatter before continuing in the
+/// background
+pub(crate) const QUICK_FORMAT_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// What a finished format is for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FormatPurpose {
+    /// Format Buffer
+    Manual,
+    /// Format-on-save with a stdin-mode formatter; the buffer is saved after
+    BeforeSave,
+    /// Format-on-save with a file-mode formatter; the saved file is reloaded
+    AfterSave,
+}
+
+/// Bookkeeping for a buffer being formatted
+pub(crate) struct PendingFormat {
+    job: FormatJob,
+    purpose: FormatPurpose,
+    formatter: FormatterConfig,
+    /// Event log position of the text being formatted
+    log_index: usize,
+    /// Copy of the buffer that a file-mode Format Buffer works on
+    copy: Option<PathBuf>,
+    /// Progress entry shown while it runs in the background
+    progress_id: Option<u32>,
+}
+
+impl Editor {
+    /// Whether a buffer is being formatted
+    pub fn is_buffer_formatting(&self, buffer_id: BufferId) -> bool {
+        self.formats.contains_key(&buffer_id)
+    }
+
+    /// Format the active buffer with its language's formatter, without saving
+    pub fn format_buffer(&mut self) -> Result<(), String> {
+        let buffer_id = self.active_buffer();
+        if self.formats.contains_key(&buffer_id) {
+            return Err(t!("format.in_progress").to_string());
+        }
+        let (language, formatter, path) = self.buffer_formatter(buffer_id)?;
+        self.authorize_formatter(&language, &formatter)?;
+
+        let (input, copy) = if formatter.stdin {
+            (FormatInput::Stdin(self.text_to_format(buffer_id)?), None)
+        } else {
+            let copy = self
+                .write_format_copy(buffer_id, &path)
+                .map_err(|e| e.to_string())?;
+            (FormatInput::File(copy.clone()), Some(copy))
+        };
+        let file = copy.clone().unwrap_or_else(|| path.clone());
+        self.start_format(
+            buffer_id,
+            FormatPurpose::Manual,
+            formatter,
+            &file,
+            input,
+            copy,
+        );
+        Ok(())
+    }
+
+    /// Save the active buffer, formatting it first if its language formats
+    /// on save
+    ///
+    /// Languages with `save_actions` run those instead (see
+    /// [`super::save_actions`]). Used for saves the user asks for; see the
+    /// module docs.
+    pub(super) fn save_with_format(&mut self) -> io::Result<()> {
+        let buffer_id = self.active_buffer();
+        if self.formats.contains_key(&buffer_id) {
+            self.set_status_message(t!("format.in_progress").to_string());
+            return Ok(());
+        }
+        if self.has_save_actions(buffer_id) {
+            return self.run_save_actions(true);
+        }
+        let Ok((language, formatter, path)) = self.buffer_formatter(buffer_id) else {
+            return self.save();
+        };
+        let format_on_save = self
+            .config
+            .languages
+            .get(&language)
+            .is_some_and(|config| config.format_on_save);
+        if !format_on_save {
+            return self.save();
+        }
+        // A formatter waiting for approval doesn't hold up the save
+        if let Err(message) = self.authorize_formatter(&language, &formatter) {
+            self.save()?;
+            self.set_status_message(message);
+            return Ok(());
+        }
+
+        if !formatter.stdin {
+            if is_remote_path(&path) {
+                let error = t!("format.remote_file_mode").to_string();
+                return self.save_after_failed_format(buffer_id, &formatter, error);
+            }
+            self.format_after_save.insert(buffer_id);
+            let result = self.save();
+            if result.is_err() {
+                self.format_after_save.remove(&buffer_id);
+            }
+            return result;
+        }
+
+        let text = match self.text_to_format(buffer_id) {
+            Ok(text) => text,
+            Err(error) => return self.save_after_failed_format(buffer_id, &formatter, error),
+        };
+        self.start_format(
+            buffer_id,
+            FormatPurpose::BeforeSave,
+            formatter,
+            &path,
+            FormatInput::Stdin(text),
+            None,
+        );
+        Ok(())
+    }
+
+    /// Run a file-mode formatter on a buffer's file once it was saved
+    pub(super) fn format_saved_file(&mut self, buffer_id: BufferId) {
+        let Ok((_, formatter, path)) = self.buffer_formatter(buffer_id) else {
+            return;
+        };
+        self.start_format(
+            buffer_id,
+            FormatPurpose::AfterSave,
+            formatter,
+            &path,
+            FormatInput::File(path.clone()),
+            None,
+        );
+    }
+
+    /// The formatter of a buffer's language, with the language and the
+    /// buffer's file
+    pub(super) fn buffer_formatter(
+        &self,
+        buffer_id: BufferId,
+    ) -> Result<(String, FormatterConfig, PathBuf), String> {
+        let path = self
+            .buffers
+            .get(&buffer_id)
+            .and_then(|state| state.buffer.file_path())
+            .map(Path::to_path_buf)
+            .ok_or_else(|| {
+                "Cannot format unsaved buffer (save first to detect language)".to_string()
+            })?;
+        let language = detect_language(&path, &self.config.languages)
+            .ok_or_else(|| "No language detected for this file".to_string())?;
+        let formatter = self
+            .config
+            .languages
+            .get(&language)
+            .and_then(|config| config.formatter.clone())
+            .ok_or_else(|| format!("No formatter configured for {}", language))?;
+        Ok((language, formatter, path))
+    }
+
+    /// Check that the formatter may run: one named by the project's config
+    /// waits for the user's approval
+    pub(super) fn authorize_formatter(
+        &self,
+        language: &str,
+        formatter: &FormatterConfig,
+    ) -> Result<(), String> {
+        let request = CommandRequest::formatter(language, formatter);
+        if self.command_gate.authorize(&request) {
+            Ok(())
+        } else {
+            Err(t!("command_trust.held_back", command = request.command_line).to_string())
+        }
+    }
+
+    pub(super) fn text_to_format(&self, buffer_id: BufferId) -> Result<String, String> {
+        self.buffers
+            .get(&buffer_id)
+            .and_then(|state| state.buffer.to_string())
+            .ok_or_else(|| t!("format.too_large").to_string())
+    }
+
+    /// Write a buffer's text to a hidden file next to its own, for a
+    /// file-mode formatter to rewrite
+    ///
+    /// Next to the file, the formatter finds the same project settings; the
+    /// name keeps the extension, which formatters use to pick a language.
+    pub(super) fn write_format_copy(
+        &self,
+        buffer_id: BufferId,
+        path: &Path,
+    ) -> io::Result<PathBuf> {
+        if is_remote_path(path) {
+            return Err(io::Error::other(t!("format.remote_file_mode").to_string()));
+        }
+        let text = self.text_to_format(buffer_id).map_err(io::Error::other)?;
+        let name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let copy = path.with_file_name(format!(".fresh-format-{}-{}", std::process::id(), name));
+        std::fs::write(&copy, text)?;
+        Ok(copy)
+    }
+
+    /// Start a formatter, finishing it right away if it is quick
+    fn start_format(
+        &mut self,
+        buffer_id: BufferId,
+        purpose: FormatPurpose,
+        formatter: FormatterConfig,
+        file: &Path,
+        input: FormatInput,
+        copy: Option<PathBuf>,
+    ) {
+        let working_dir = std::env::current_dir()
+            .unwrap_or_else(|_| file.parent().unwrap_or(Path::new(".")).to_path_buf());
+        let command = FormatCommand::new(&formatter.command, &formatter.args, file, &working_dir)
+            .with_timeout(Duration::from_millis(formatter.timeout_ms));
+        let log_index = self
+            .event_logs
+            .get(&buffer_id)
+            .map_or(0, |log| log.current_index());
+        let job = FormatJob::start(&self.worker_pool, command, input, file);
+        let mut pending = PendingFormat {
+            job,
+            purpose,
+            formatter,
+            log_index,
+            copy,
+            progress_id: None,
+        };
+        if let Some(result) = pending.job.wait(QUICK_FORMAT_TIMEOUT) {
+            self.finish_format(buffer_id, pending, result);
+            return;
+        }
+
+        let name = self
+            .buffers
+            .get(&buffer_id)
+            .and_then(|state| state.buffer.file_path())
+            .map(display_file_name)
+            .unwrap_or_default();
+        tracing::info!("Formatting {} in the background", name);
+        let progress_id = self.start_progress(t!("format.running", name = name).to_string());
+        pending.progress_id = Some(progress_id);
+        self.formats.insert(buffer_id, pending);
+    }
+
+    /// Finish formatters that have ended
+    ///
+    /// Returns true if anything visible changed.
+    pub(super) fn poll_formats(&mut self) -> bool {
+        if self.formats.is_empty() {
+            return false;
+        }
+        let finished: Vec<_> = self
+            .formats
+            .iter_mut()
+            .filter_map(|(buffer_id, pending)| {
+                pending.job.poll().map(|result| (*buffer_id, result))
+            })
+            .collect();
+        let changed = !finished.is_empty();
+        for (buffer_id, result) in finished {
+            if let Some(pending) = self.formats.remove(&buffer_id) {
+                self.finish_format(buffer_id, pending, result);
+            }
+        }
+        changed
+    }
+
+    /// Stop formatting a buffer that is being closed
+    pub(super) fn on_formatted_buffer_closed(&mut self, buffer_id: BufferId) {
+        self.format_after_save.remove(&buffer_id);
+        if let Some(pending) = self.formats.remove(&buffer_id) {
+            pending.job.cancel();
+            if let Some(progress_id) = pending.progress_id {
+                self.finish_progress(progress_id);
+            }
+            if let Some(copy) = &pending.copy {
+                let _ = std::fs::remove_file(copy);
+            }
+        }
+    }
+
+    fn finish_format(
+        &mut self,
+        buffer_id: BufferId,
+        pending: PendingFormat,
+        result: Result<String, FormatError>,
+    ) {
+        let PendingFormat {
+            purpose,
+            formatter,
+            log_index,
+            copy,
+            progress_id,
+            ..
+        } = pending;
+        if let Some(progress_id) = progress_id {
+            self.finish_progress(progress_id);
+        }
+        if let Some(copy) = &copy {
+            let _ = std::fs::remove_file(copy);
+        }
+        let Some(path) = self
+            .buffers
+            .get(&buffer_id)
+            .and_then(|state| state.buffer.file_path())
+            .map(Path::to_path_buf)
+        else {
+            return;
+        };
+        let name = display_file_name(&path);
+
+        let text = match result {
+            Ok(text) => text,
+            Err(error) => {
+                tracing::warn!("Formatting {} failed: {}", path.display(), error);
+                match purpose {
+                    FormatPurpose::BeforeSave => {
+                        if let Err(e) =
+                            self.save_after_failed_format(buffer_id, &formatter, error.to_string())
+                        {
+                            self.report_failed_save(buffer_id, &path, e);
+                        }
+                    }
+                    FormatPurpose::Manual | FormatPurpose::AfterSave => {
+                        let message =
+                            t!("error.format_failed", error = error.to_string()).to_string();
+                        self.set_status_message(message.clone());
+                        self.notify(NotificationSeverity::Error, message);
+                    }
+                }
+                return;
+            }
+        };
+
+        // The text was formatted as it was when formatting started
+        let current = self
+            .event_logs
+            .get(&buffer_id)
+            .map_or(0, |log| log.current_index());
+        let applicable = current == log_index && self.active_buffer() == buffer_id;
+        let formatted = t!("format.formatted_with", formatter = &formatter.command).to_string();
+        match purpose {
+            FormatPurpose::Manual => {
+                if applicable {
+                    self.apply_formatted_text(buffer_id, &text, &formatter.command);
+                    self.set_status_message(formatted);
+                } else {
+                    self.set_status_message(t!("format.stale", name = name).to_string());
+                }
+            }
+            FormatPurpose::BeforeSave => {
+                if applicable {
+                    self.apply_formatted_text(buffer_id, &text, &formatter.command);
+                }
+                match self.save_buffer(buffer_id) {
+                    // A save still running reports when it's done
+                    Ok(()) if self.is_buffer_saving(buffer_id) => {}
+                    Ok(()) if applicable => self.set_status_message(
+                        t!("format.saved_formatted", formatter = &formatter.command).to_string(),
+                    ),
+                    Ok(()) => self.set_status_message(t!("format.stale", name = name).to_string()),
+                    Err(e) => self.report_failed_save(buffer_id, &path, e),
+                }
+            }
+            FormatPurpose::AfterSave => {
+                let unedited = self
+                    .buffers
+                    .get(&buffer_id)
+                    .is_some_and(|state| !state.buffer.is_modified());
+                if !(unedited && self.active_buffer() == buffer_id) {
+                    // The file changed on disk; the usual checks take it from here
+                    self.set_status_message(t!("format.stale", name = name).to_string());
+                    return;
+                }
+                let description = t!("undo.format", formatter = &formatter.command).to_string();
+                match self.replace_with_saved(buffer_id, description) {
+                    Ok(()) => self.set_status_message(formatted),
+                    Err(e) => self.set_status_message(
+                        t!("error.format_failed", error = e.to_string()).to_string(),
+                    ),
+                }
+            }
+        }
+    }
+
+    /// Replace the active buffer's text with what `formatter` made of it, as
+    /// one undo step
+    fn apply_formatted_text(&mut self, buffer_id: BufferId, text: &str, formatter: &str) {
+        let Some(old) = self
+            .buffers
+            .get(&buffer_id)
+            .and_then(|state| state.buffer.get_all_text())
+        else {
+            return;
+        };
+        let description = t!("undo.format", formatter = formatter).to_string();
+        self.replace_changed_lines(&old, text.as_bytes(), description);
+    }
+
+    /// Handle format-on-save failing: show why, then save the unformatted
+    /// text unless the formatter says to abort
+    fn save_after_failed_format(
+        &mut self,
+        buffer_id: BufferId,
+        formatter: &FormatterConfig,
+        error: String,
+    ) -> io::Result<()> {
+        let message = match formatter.on_failure {
+            FormatFailure::Save => {
+                self.save_buffer(buffer_id)?;
+                t!("format.failed_saved", error = error).to_string()
+            }
+            FormatFailure::Abort => t!("format.failed_not_saved", error = error).to_string(),
+        };
+        self.set_status_message(message.clone());
+        self.notify(NotificationSeverity::Error, message);
+        Ok(())
+    }
+}
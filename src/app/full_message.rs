@@ -0,0 +1,59 @@
+//! "Show Full Message"
+//!
+//! The status bar shortens messages too long for it (see
+//! [`crate::primitives::truncate::fit_message`]). This command opens the
+//! whole of the current status message in a popup that wraps it; Enter copies
+//! it to the clipboard.
+
+use crate::view::popup::{Popup, PopupPosition};
+use ratatui::style::Style;
+use rust_i18n::t;
+
+use super::Editor;
+
+impl Editor {
+    /// Open the current status message in a popup
+    pub(super) fn show_full_message(&mut self) {
+        let Some(message) = self
+            .get_status_message()
+            .filter(|message| !message.is_empty())
+            .cloned()
+        else {
+            self.set_status_message(t!("status.no_message").to_string());
+            return;
+        };
+
+        let lines: Vec<String> = message.lines().map(str::to_string).collect();
+        let title = t!("status.full_message_title").to_string();
+        let mut popup = Popup::text(lines, &self.theme);
+        popup.title = Some(title.clone());
+        popup.description = Some(t!("status.full_message_hint").to_string());
+        popup.position = PopupPosition::Centered;
+        popup.width = self.terminal_width.saturating_sub(4).clamp(20, 100);
+        popup.max_height = (self.terminal_height * 60 / 100).clamp(5, 30);
+        popup.border_style = Style::default().fg(self.theme.popup_border_fg);
+        popup.background_style = Style::default().bg(self.theme.popup_bg);
+        self.full_message = Some((title, message));
+        self.active_state_mut().popups.show(popup);
+    }
+
+    /// Whether the top popup is the one opened by "Show Full Message"
+    pub(super) fn is_full_message_popup(&self) -> bool {
+        let Some((title, _)) = &self.full_message else {
+            return false;
+        };
+        self.active_state()
+            .popups
+            .top()
+            .and_then(|popup| popup.title.as_ref())
+            .is_some_and(|popup_title| popup_title == title)
+    }
+
+    /// Copy the message shown in the popup to the clipboard
+    pub(super) fn copy_full_message(&mut self) {
+        if let Some((_, message)) = self.full_message.take() {
+            self.clipboard.copy(message);
+            self.set_status_message(t!("status.full_message_copied").to_string());
+        }
+    }
+}
@@ -0,0 +1,249 @@
+//! Go to a byte offset or a percentage of the buffer
+//!
+//! Offsets usually come from other tools (`grep --byte-offset`, parser
+//! errors), so they are accepted in decimal or `0x` hex and clamped to the
+//! buffer. The cursor lands on the character boundary at or before the
+//! offset. A percentage lands on the start of the line containing that
+//! proportion of the bytes.
+//!
+//! Both only read the text around the landing point, so they stay cheap on
+//! large files whose contents are loaded in chunks. The goto-line prompt
+//! accepts `:50%` and `:byte 12345` for the same jumps.
+
+use rust_i18n::t;
+
+use crate::model::buffer::Buffer;
+use crate::model::event::{BufferId, Event};
+
+use super::Editor;
+
+/// What the goto-line prompt input asks for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum GotoInput<'a> {
+    /// A line number (the whole input)
+    Line(&'a str),
+    /// `50%`: a percentage of the buffer
+    Percentage(&'a str),
+    /// `byte 12345`: a byte offset
+    Byte(&'a str),
+}
+
+/// Split goto-line prompt input into the kind of jump and its argument
+///
+/// A leading `:` is optional.
+pub(super) fn classify_goto_input(input: &str) -> GotoInput<'_> {
+    let input = input.trim();
+    let input = input.strip_prefix(':').unwrap_or(input).trim_start();
+    if let Some(offset) = input.strip_prefix("byte") {
+        return GotoInput::Byte(offset.trim());
+    }
+    if input.ends_with('%') {
+        return GotoInput::Percentage(input);
+    }
+    GotoInput::Line(input)
+}
+
+/// Parse a byte offset in decimal or `0x` hex; `_` separators are allowed
+pub(super) fn parse_byte_offset(input: &str) -> Option<usize> {
+    let digits: String = input.trim().chars().filter(|&c| c != '_').collect();
+    match digits
+        .strip_prefix("0x")
+        .or_else(|| digits.strip_prefix("0X"))
+    {
+        Some(hex) => usize::from_str_radix(hex, 16).ok(),
+        None => digits.parse().ok(),
+    }
+}
+
+/// Parse a percentage from 0 to 100, with or without a trailing `%`
+pub(super) fn parse_percentage(input: &str) -> Option<f64> {
+    let input = input.trim();
+    let number = input.strip_suffix('%').unwrap_or(input).trim();
+    number
+        .parse::<f64>()
+        .ok()
+        .filter(|p| (0.0..=100.0).contains(p))
+}
+
+/// Byte offset at `percent` of a buffer of `len` bytes
+fn percentage_offset(len: usize, percent: f64) -> usize {
+    ((len as f64 * percent / 100.0) as usize).min(len)
+}
+
+/// Where the cursor lands for `offset`: clamped to the buffer and moved back
+/// to the start of the character containing it
+fn byte_landing(buffer: &mut Buffer, offset: usize) -> usize {
+    let len = buffer.len();
+    let offset = offset.min(len);
+    if offset == len {
+        return len;
+    }
+    // Load the bytes a UTF-8 character can span before reading them
+    let start = offset.saturating_sub(3);
+    let _ = buffer.get_text_range_mut(start, offset + 1 - start);
+    buffer.snap_to_char_boundary(offset)
+}
+
+/// Start of the line containing `offset`, scanning back from it
+fn line_start_at(buffer: &mut Buffer, offset: usize, estimated_line_length: usize) -> usize {
+    buffer
+        .line_iterator(offset, estimated_line_length)
+        .current_position()
+}
+
+impl Editor {
+    /// Move the cursor to a byte offset in the active buffer
+    ///
+    /// Returns the position the cursor landed on.
+    pub fn goto_byte(&mut self, offset: usize) -> usize {
+        let position = byte_landing(&mut self.active_state_mut().buffer, offset);
+        self.jump_and_center(position);
+        position
+    }
+
+    /// Move the cursor to the start of the line at `percent` (0-100) of the
+    /// active buffer
+    ///
+    /// Returns the position the cursor landed on.
+    pub fn goto_percentage(&mut self, percent: f64) -> usize {
+        let estimated_line_length = self.config.editor.estimated_line_length;
+        let buffer = &mut self.active_state_mut().buffer;
+        let offset = percentage_offset(buffer.len(), percent);
+        let position = line_start_at(buffer, offset, estimated_line_length);
+        self.jump_and_center(position);
+        position
+    }
+
+    /// Move the primary cursor and scroll it to the middle of the view
+    fn jump_and_center(&mut self, position: usize) {
+        let cursor = *self.active_state().cursors.primary();
+        let event = Event::MoveCursor {
+            cursor_id: self.active_state().cursors.primary_id(),
+            old_position: cursor.position,
+            new_position: position,
+            old_anchor: cursor.anchor,
+            new_anchor: None,
+            old_sticky_column: cursor.sticky_column,
+            new_sticky_column: 0,
+        };
+        self.apply_event_to_active_buffer(&event);
+        self.apply_event_to_active_buffer(&Event::Recenter);
+    }
+
+    /// Answer to the "Go to byte offset" prompt
+    pub(super) fn handle_goto_byte_input(&mut self, input: &str) {
+        match parse_byte_offset(input) {
+            Some(offset) => {
+                let position = self.goto_byte(offset);
+                self.set_status_message(t!("goto.jumped_byte", offset = position).to_string());
+            }
+            None => {
+                self.set_status_message(t!("error.invalid_byte_offset", input = input).to_string());
+            }
+        }
+    }
+
+    /// Answer to the "Go to percentage" prompt
+    pub(super) fn handle_goto_percentage_input(&mut self, input: &str) {
+        match parse_percentage(input) {
+            Some(percent) => {
+                let position = self.goto_percentage(percent);
+                self.set_status_message(
+                    t!(
+                        "goto.jumped_percentage",
+                        percent = percent,
+                        offset = position
+                    )
+                    .to_string(),
+                );
+            }
+            None => {
+                self.set_status_message(t!("error.invalid_percentage", input = input).to_string());
+            }
+        }
+    }
+
+    /// Handle `editor.gotoByte` from a plugin, making the buffer active first
+    pub(super) fn handle_goto_byte_command(&mut self, buffer_id: BufferId, offset: usize) {
+        if !self.buffers.contains_key(&buffer_id) {
+            tracing::warn!("Buffer {:?} not found for GotoByte", buffer_id);
+            return;
+        }
+        self.set_active_buffer(buffer_id);
+        self.goto_byte(offset);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify_goto_input() {
+        assert_eq!(classify_goto_input("42"), GotoInput::Line("42"));
+        assert_eq!(classify_goto_input(" :42 "), GotoInput::Line("42"));
+        assert_eq!(classify_goto_input(":50%"), GotoInput::Percentage("50%"));
+        assert_eq!(classify_goto_input("12.5%"), GotoInput::Percentage("12.5%"));
+        assert_eq!(classify_goto_input(":byte 12345"), GotoInput::Byte("12345"));
+        assert_eq!(classify_goto_input("byte 0x1F"), GotoInput::Byte("0x1F"));
+    }
+
+    #[test]
+    fn test_parse_byte_offset() {
+        assert_eq!(parse_byte_offset("12345"), Some(12345));
+        assert_eq!(parse_byte_offset("183_449_201"), Some(183_449_201));
+        assert_eq!(parse_byte_offset("0x1F"), Some(31));
+        assert_eq!(parse_byte_offset("0XfF"), Some(255));
+        assert_eq!(parse_byte_offset("-5"), None);
+        assert_eq!(parse_byte_offset("0x"), None);
+        assert_eq!(parse_byte_offset("ten"), None);
+        assert_eq!(parse_byte_offset(""), None);
+    }
+
+    #[test]
+    fn test_parse_percentage() {
+        assert_eq!(parse_percentage("50%"), Some(50.0));
+        assert_eq!(parse_percentage("12.5"), Some(12.5));
+        assert_eq!(parse_percentage("0"), Some(0.0));
+        assert_eq!(parse_percentage("100%"), Some(100.0));
+        assert_eq!(parse_percentage("101%"), None);
+        assert_eq!(parse_percentage("-1"), None);
+        assert_eq!(parse_percentage("NaN"), None);
+        assert_eq!(parse_percentage("%"), None);
+    }
+
+    #[test]
+    fn test_byte_landing_clamps_to_buffer() {
+        let mut buffer = Buffer::from_str_test("hello\nworld\n");
+        assert_eq!(byte_landing(&mut buffer, 0), 0);
+        assert_eq!(byte_landing(&mut buffer, 7), 7);
+        assert_eq!(byte_landing(&mut buffer, 12), 12);
+        assert_eq!(byte_landing(&mut buffer, 1_000_000), 12);
+    }
+
+    #[test]
+    fn test_byte_landing_snaps_to_char_boundary() {
+        // "a" at 0, "é" at 1..3, "€" at 3..6, "😀" at 6..10, "b" at 10
+        let mut buffer = Buffer::from_str_test("aé€😀b");
+        assert_eq!(byte_landing(&mut buffer, 2), 1);
+        assert_eq!(byte_landing(&mut buffer, 4), 3);
+        assert_eq!(byte_landing(&mut buffer, 5), 3);
+        assert_eq!(byte_landing(&mut buffer, 9), 6);
+        assert_eq!(byte_landing(&mut buffer, 10), 10);
+    }
+
+    #[test]
+    fn test_percentage_lands_on_line_start() {
+        let text = "0123456789\n".repeat(10);
+        let mut buffer = Buffer::from_str_test(&text);
+        let line_start = |buffer: &mut Buffer, percent| {
+            let offset = percentage_offset(buffer.len(), percent);
+            line_start_at(buffer, offset, 80)
+        };
+        assert_eq!(line_start(&mut buffer, 0.0), 0);
+        // 50% of 110 bytes is byte 55, in the sixth line
+        assert_eq!(line_start(&mut buffer, 50.0), 55);
+        assert_eq!(line_start(&mut buffer, 52.0), 55);
+        assert_eq!(line_start(&mut buffer, 100.0), 110);
+    }
+}
@@ -0,0 +1,108 @@
+//! Columns of the left gutter
+//!
+//! The gutter is composed from the built-in "indicators" and "line_numbers"
+//! columns and the columns plugins register with
+//! `editor.registerGutterColumn(name, width, alignment)`.
+//! `editor.gutter_columns` orders them and `editor.gutter_column_widths`
+//! resizes or hides them (see [`GutterColumn::layout`]). Plugins fill their
+//! columns with `setLineIndicator(..., column)`.
+//!
+//! The layout is kept on the editor and copied to every buffer's margins
+//! before rendering.
+
+use super::Editor;
+use crate::model::event::BufferId;
+use crate::services::plugins::hooks::HookArgs;
+use crate::view::margin::{GutterAlignment, GutterColumn, MarginManager};
+
+/// Plugin columns and the layout computed from them and the config
+#[derive(Debug, Default)]
+pub(super) struct GutterColumns {
+    /// Columns registered by plugins, in registration order
+    plugin_columns: Vec<GutterColumn>,
+    /// Columns drawn in every buffer's gutter, left to right
+    layout: Vec<GutterColumn>,
+}
+
+impl GutterColumns {
+    /// Copy the layout to a buffer's margins if it changed
+    pub(super) fn sync(&self, margins: &mut MarginManager) {
+        if margins.left_config.columns != self.layout {
+            margins.set_columns(self.layout.clone());
+        }
+    }
+}
+
+impl Editor {
+    /// Columns drawn in the gutter, left to right
+    pub fn gutter_layout(&self) -> &[GutterColumn] {
+        &self.gutter_columns.layout
+    }
+
+    /// Recompute the gutter layout from the config and the plugin columns
+    pub(super) fn apply_gutter_columns(&mut self) {
+        self.gutter_columns.layout = GutterColumn::layout(
+            &self.config.editor.gutter_columns,
+            &self.config.editor.gutter_column_widths,
+            &self.gutter_columns.plugin_columns,
+        );
+    }
+
+    /// Add a plugin column, or change one registered before
+    pub(super) fn handle_register_gutter_column(
+        &mut self,
+        name: String,
+        width: usize,
+        alignment: GutterAlignment,
+    ) {
+        let column = GutterColumn::plugin(name, width, alignment);
+        let columns = &mut self.gutter_columns.plugin_columns;
+        match columns.iter_mut().find(|c| c.name() == column.name()) {
+            Some(existing) => *existing = column,
+            None => columns.push(column),
+        }
+        self.apply_gutter_columns();
+    }
+
+    /// Remove a plugin column and the indicators shown in it
+    pub(super) fn handle_unregister_gutter_column(&mut self, name: String) {
+        self.gutter_columns
+            .plugin_columns
+            .retain(|column| column.name() != name);
+        for state in self.buffers.values_mut() {
+            state.margins.clear_line_indicators_for_column(&name);
+        }
+        self.apply_gutter_columns();
+    }
+
+    /// The gutter column at `x` cells from the left of a buffer's gutter and
+    /// the line at `position`, for the `gutter_clicked` hook
+    pub(super) fn gutter_click_target(
+        &self,
+        buffer_id: BufferId,
+        x: usize,
+        position: usize,
+    ) -> Option<(String, usize)> {
+        let state = self.buffers.get(&buffer_id)?;
+        let column = state.margins.left_config.column_at(x)?;
+        Some((
+            column.name().to_string(),
+            state.buffer.get_line_number(position),
+        ))
+    }
+
+    /// Tell plugins a gutter column was clicked
+    pub(super) fn fire_gutter_clicked(&self, buffer_id: BufferId, line: usize, column: String) {
+        if !self.plugin_manager.has_hook_handlers("gutter_clicked") {
+            return;
+        }
+        self.plugin_manager.run_hook(
+            "gutter_clicked",
+            HookArgs::GutterClicked {
+                buffer_id,
+                line,
+                column,
+            },
+        );
+    }
+}
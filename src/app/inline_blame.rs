@@ -0,0 +1,386 @@
+//! Inline git blame
+//!
+//! "Toggle Inline Blame" annotates the cursor line with the author, age and
+//! summary of the commit that last changed it; "Toggle Inline Blame (All
+//! Lines)" annotates every line on screen. The annotation is a dimmed virtual
+//! line above the line or text after it, per `editor.inline_blame_position`.
+//!
+//! Git runs once per toggle, on the worker pool (see
+//! [`crate::services::git_blame`]). After that, moving the cursor or
+//! scrolling only looks lines up: edits are matched against the blamed text
+//! again when the buffer revision changes, and lines added or changed since
+//! show "Not committed yet". "Show Blame Commit" opens the full message of
+//! the cursor line's commit in a popup.
+
+use crate::config::InlineBlamePosition;
+use crate::model::event::BufferId;
+use crate::services::git_blame::{
+    self, age, map_lines, Age, Blame, BlameError, BlameRequest, BlameSnapshot,
+};
+use crate::services::worker_pool::JobHandle;
+use crate::view::popup::{Popup, PopupPosition};
+use crate::view::virtual_text::{VirtualTextNamespace, VirtualTextPosition};
+use ratatui::layout::Rect;
+use ratatui::style::{Modifier, Style};
+use rust_i18n::t;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use super::Editor;
+
+/// Namespace (virtual lines) and string id (end-of-line text) of the annotations
+const BLAME_ID: &str = "inline-blame";
+
+/// Inline blame of one buffer
+pub(crate) struct InlineBlame {
+    path: PathBuf,
+    /// Annotate every line on screen instead of the cursor line
+    all_lines: bool,
+    /// Finished blame (None while git runs)
+    blame: Option<Arc<Blame>>,
+    /// Blamed line of each buffer line, for the buffer revision it was made at
+    line_map: Option<(u64, Vec<Option<usize>>)>,
+    /// Blame in progress and its id
+    request: Option<(u64, JobHandle)>,
+    /// Annotations on screen as (line start, text), to skip redrawing them
+    shown: Vec<(usize, String)>,
+}
+
+impl InlineBlame {
+    /// Commit of a 0-based buffer line, or None if the line isn't committed
+    fn commit_of_line(&self, line: usize) -> Option<&git_blame::BlameCommit> {
+        let blame = self.blame.as_ref()?;
+        let (_, map) = self.line_map.as_ref()?;
+        let blamed = map.get(line).copied().flatten()?;
+        blame
+            .commit_of_line(blamed)
+            .filter(|commit| !commit.is_uncommitted())
+    }
+
+    /// Annotation text for a 0-based buffer line
+    fn annotation(&self, line: usize, now: i64) -> String {
+        match self.commit_of_line(line) {
+            Some(commit) => t!(
+                "blame.annotation",
+                author = commit.author,
+                age = format_age(age(commit.author_time, now)),
+                summary = commit.summary
+            )
+            .to_string(),
+            None => t!("blame.not_committed").to_string(),
+        }
+    }
+}
+
+/// Localized "3d ago" for an [`Age`]
+fn format_age(age: Age) -> String {
+    match age {
+        Age::JustNow => t!("blame.just_now"),
+        Age::Minutes(count) => t!("blame.minutes_ago", count = count),
+        Age::Hours(count) => t!("blame.hours_ago", count = count),
+        Age::Days(count) => t!("blame.days_ago", count = count),
+        Age::Weeks(count) => t!("blame.weeks_ago", count = count),
+        Age::Months(count) => t!("blame.months_ago", count = count),
+        Age::Years(count) => t!("blame.years_ago", count = count),
+    }
+    .to_string()
+}
+
+impl Editor {
+    /// Toggle inline blame for the active buffer
+    ///
+    /// `all_lines` picks the variant annotating every line on screen.
+    /// Toggling the other variant while one is on switches to it.
+    pub fn toggle_inline_blame(&mut self, all_lines: bool) {
+        static NEXT_REQUEST_ID: AtomicU64 = AtomicU64::new(1);
+
+        let buffer_id = self.active_buffer();
+        if let Some(existing) = self.inline_blame.get_mut(&buffer_id) {
+            if existing.all_lines == all_lines {
+                self.remove_inline_blame(buffer_id);
+                self.set_status_message(t!("blame.disabled").to_string());
+            } else {
+                existing.all_lines = all_lines;
+                self.set_status_message(t!("blame.enabled").to_string());
+            }
+            return;
+        }
+
+        let Some(path) = self
+            .buffer_metadata
+            .get(&buffer_id)
+            .and_then(|metadata| metadata.file_path())
+            .cloned()
+        else {
+            self.set_status_message(t!("blame.no_file").to_string());
+            return;
+        };
+        if self.active_state().buffer.is_large_file() {
+            self.set_status_message(t!("blame.too_large").to_string());
+            return;
+        }
+
+        let request_id = NEXT_REQUEST_ID.fetch_add(1, Ordering::Relaxed);
+        let job = git_blame::start_blame(
+            &self.worker_pool,
+            BlameRequest {
+                request_id,
+                path: path.clone(),
+                cached: self.blame_cache.get(&path).cloned(),
+            },
+        );
+        self.inline_blame.insert(
+            buffer_id,
+            InlineBlame {
+                path,
+                all_lines,
+                blame: None,
+                line_map: None,
+                request: Some((request_id, job)),
+                shown: Vec::new(),
+            },
+        );
+        self.set_status_message(t!("blame.loading").to_string());
+    }
+
+    /// Take in a finished `git blame`
+    pub(super) fn handle_git_blame_finished(
+        &mut self,
+        request_id: u64,
+        path: PathBuf,
+        result: Result<BlameSnapshot, BlameError>,
+    ) {
+        if let Ok(snapshot) = &result {
+            self.blame_cache.insert(path.clone(), snapshot.clone());
+        }
+        let Some(buffer_id) = self.inline_blame.iter().find_map(|(id, blame)| {
+            let pending = blame.request.as_ref().map(|(id, _)| *id);
+            (pending == Some(request_id)).then_some(*id)
+        }) else {
+            return;
+        };
+
+        match result {
+            Ok(snapshot) => {
+                if let Some(blame) = self.inline_blame.get_mut(&buffer_id) {
+                    blame.request = None;
+                    blame.blame = Some(snapshot.blame);
+                }
+                self.set_status_message(t!("blame.enabled").to_string());
+            }
+            Err(BlameError::NotInGit) => {
+                self.remove_inline_blame(buffer_id);
+                let name = path
+                    .file_name()
+                    .map(|name| name.to_string_lossy().into_owned())
+                    .unwrap_or_default();
+                self.set_status_message(t!("blame.not_in_git", file = name).to_string());
+            }
+            Err(BlameError::Failed(error)) => {
+                self.remove_inline_blame(buffer_id);
+                self.set_status_message(t!("blame.error", error = error).to_string());
+            }
+        }
+    }
+
+    /// Show the full message of the cursor line's commit in a popup
+    pub fn show_blame_commit(&mut self) {
+        let buffer_id = self.active_buffer();
+        let Some(blame) = self.inline_blame.get(&buffer_id) else {
+            self.set_status_message(t!("blame.off").to_string());
+            return;
+        };
+        if blame.blame.is_none() {
+            self.set_status_message(t!("blame.loading").to_string());
+            return;
+        }
+        let state = self.buffers.get(&buffer_id);
+        let line = state.map_or(0, |state| {
+            state
+                .buffer
+                .get_line_number(state.cursors.primary().position)
+        });
+        let Some(commit) = blame.commit_of_line(line) else {
+            self.set_status_message(t!("blame.not_committed").to_string());
+            return;
+        };
+        let hash = commit.hash.clone();
+        let path = blame.path.clone();
+        // The job reports back through the async bridge; nothing to cancel
+        let _ = git_blame::start_show_commit(&self.worker_pool, &path, hash);
+    }
+
+    /// Show the output of `git show` for "Show Blame Commit"
+    pub(super) fn handle_git_commit_shown(&mut self, hash: String, result: Result<String, String>) {
+        let text = match result {
+            Ok(text) => text,
+            Err(error) => {
+                self.set_status_message(t!("blame.error", error = error).to_string());
+                return;
+            }
+        };
+        let lines: Vec<String> = text.lines().map(str::to_string).collect();
+        let mut popup = Popup::text(lines, &self.theme);
+        popup.title = Some(t!("blame.commit_title", hash = &hash[..hash.len().min(8)]).to_string());
+        popup.position = PopupPosition::BelowCursor;
+        popup.width = 80;
+        popup.max_height = (self.terminal_height * 60 / 100).clamp(10, 40);
+        popup.border_style = Style::default().fg(self.theme.popup_border_fg);
+        popup.background_style = Style::default().bg(self.theme.popup_bg);
+        self.active_state_mut().popups.show(popup);
+    }
+
+    /// Bring the annotations of visible buffers up to date before rendering
+    ///
+    /// Only reads the cached blame; git doesn't run here.
+    pub(super) fn update_inline_blame(&mut self, editor_area: Rect) {
+        if self.inline_blame.is_empty() {
+            return;
+        }
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs() as i64);
+        let placement = self.config.editor.inline_blame_position;
+        let estimated_line_length = self.config.editor.estimated_line_length;
+        let style = Style::default()
+            .fg(self.theme.line_number_fg)
+            .add_modifier(Modifier::ITALIC);
+
+        // Byte ranges on screen as (top byte, lines), per buffer
+        let mut visible: HashMap<BufferId, Vec<(usize, usize)>> = HashMap::new();
+        for (split_id, buffer_id, area) in self.split_manager.get_visible_buffers(editor_area) {
+            let top_byte = self
+                .split_view_states
+                .get(&split_id)
+                .map_or(0, |view| view.viewport.top_byte);
+            visible
+                .entry(buffer_id)
+                .or_default()
+                .push((top_byte, area.height as usize));
+        }
+
+        for (buffer_id, screens) in visible {
+            let (Some(blame), Some(state)) = (
+                self.inline_blame.get_mut(&buffer_id),
+                self.buffers.get_mut(&buffer_id),
+            ) else {
+                continue;
+            };
+            let Some(blamed) = blame.blame.clone() else {
+                continue;
+            };
+
+            let revision = state.buffer.revision();
+            if blame.line_map.as_ref().map(|(r, _)| *r) != Some(revision) {
+                let Some(text) = state.buffer.to_string() else {
+                    continue;
+                };
+                blame.line_map = Some((revision, map_lines(&blamed.text, text.as_bytes())));
+            }
+
+            let ranges = if blame.all_lines {
+                screens
+            } else {
+                vec![(state.cursors.primary().position, 1)]
+            };
+            // Lines to annotate as (line start, line content, annotation)
+            let mut wanted: Vec<(usize, String, String)> = Vec::new();
+            for (start, count) in ranges {
+                let first_line = state.buffer.get_line_number(start);
+                let mut iter = state.buffer.line_iterator(start, estimated_line_length);
+                for line in first_line..first_line + count {
+                    let Some((line_start, content)) = iter.next() else {
+                        break;
+                    };
+                    if wanted.iter().all(|(start, _, _)| *start != line_start) {
+                        wanted.push((line_start, content, blame.annotation(line, now)));
+                    }
+                }
+            }
+
+            let shown: Vec<(usize, String)> = wanted
+                .iter()
+                .map(|(start, _, text)| (*start, text.clone()))
+                .collect();
+            if shown == blame.shown {
+                continue;
+            }
+            blame.shown = shown;
+
+            let namespace = VirtualTextNamespace::from_string(BLAME_ID.to_string());
+            state
+                .virtual_texts
+                .clear_namespace(&mut state.marker_list, &namespace);
+            state
+                .virtual_texts
+                .remove_by_id(&mut state.marker_list, BLAME_ID);
+            for (line_start, content, text) in wanted {
+                match placement {
+                    InlineBlamePosition::LineAbove => {
+                        state.virtual_texts.add_line(
+                            &mut state.marker_list,
+                            line_start,
+                            text,
+                            style,
+                            VirtualTextPosition::LineAbove,
+                            namespace,
+                            0,
+                        );
+                    }
+                    InlineBlamePosition::EndOfLine => {
+                        let text_len = content.trim_end_matches(['\n', '\r']).len();
+                        let text_end = line_start + text_len;
+                        // Before the line break, or after the last character
+                        // of a last line without one
+                        let (position, side) = if text_len < content.len() {
+                            (text_end, VirtualTextPosition::BeforeChar)
+                        } else if text_len > 0 {
+                            (
+                                state.buffer.prev_char_boundary(text_end),
+                                VirtualTextPosition::AfterChar,
+                            )
+                        } else {
+                            continue;
+                        };
+                        state.virtual_texts.add_with_id(
+                            &mut state.marker_list,
+                            position,
+                            format!("    {}", text),
+                            style,
+                            side,
+                            0,
+                            BLAME_ID.to_string(),
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    /// Turn inline blame off for a buffer and remove its annotations
+    fn remove_inline_blame(&mut self, buffer_id: BufferId) {
+        let Some(blame) = self.inline_blame.remove(&buffer_id) else {
+            return;
+        };
+        if let Some((_, job)) = blame.request {
+            job.cancel();
+        }
+        if let Some(state) = self.buffers.get_mut(&buffer_id) {
+            let namespace = VirtualTextNamespace::from_string(BLAME_ID.to_string());
+            state
+                .virtual_texts
+                .clear_namespace(&mut state.marker_list, &namespace);
+            state
+                .virtual_texts
+                .remove_by_id(&mut state.marker_list, BLAME_ID);
+        }
+    }
+
+    /// Forget the inline blame of a closed buffer
+    pub(super) fn on_inline_blame_buffer_closed(&mut self, buffer_id: BufferId) {
+        self.remove_inline_blame(buffer_id);
+    }
+}
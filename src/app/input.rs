@@ -1,24 +1,19 @@
 use super::*;
+use crate::input::pipeline::{
+    resolve_key_with_layer, run_action, ActionHost, KeyResolution, KeybindingLayer, KeymapLayer,
+    ModeLayer, ReadOnlyModeLayer,
+};
+use crate::services::file_access::reason_for_error;
+use crate::services::file_provider::is_remote_path;
 use crate::services::plugins::hooks::HookArgs;
+use crate::view::file_tree::TreeRow;
+use crate::view::viewport::LinePlacement;
 use rust_i18n::t;
 impl Editor {
-    /// Determine the current keybinding context based on UI state
+    /// The keybinding context of the topmost input layer (see
+    /// [`crate::input::focus_stack`])
     pub fn get_key_context(&self) -> crate::input::keybindings::KeyContext {
-        use crate::input::keybindings::KeyContext;
-
-        // Priority order: Settings > Menu > Prompt > Popup > Rename > Current context (FileExplorer or Normal)
-        if self.settings_state.as_ref().map_or(false, |s| s.visible) {
-            KeyContext::Settings
-        } else if self.menu_state.active_menu.is_some() {
-            KeyContext::Menu
-        } else if self.is_prompting() {
-            KeyContext::Prompt
-        } else if self.active_state().popups.is_visible() {
-            KeyContext::Popup
-        } else {
-            // Use the current context (can be FileExplorer or Normal)
-            self.key_context
-        }
+        self.focus_stack().key_context(self.key_context)
     }
 
     /// Handle a key event and return whether it was handled
@@ -28,9 +23,21 @@ impl Editor {
         code: crossterm::event::KeyCode,
         modifiers: crossterm::event::KeyModifiers,
     ) -> std::io::Result<()> {
-        use crate::input::keybindings::Action;
+        self.note_input_for_word_index();
+        self.record_usage_key();
+        let result = self.dispatch_key(code, modifiers);
+        self.validate_if_enabled("key event");
+        result
+    }
 
-        let _t_total = std::time::Instant::now();
+    /// Offer a key to the input layers top-down until one consumes it
+    fn dispatch_key(
+        &mut self,
+        code: crossterm::event::KeyCode,
+        modifiers: crossterm::event::KeyModifiers,
+    ) -> std::io::Result<()> {
+        use crate::input::focus_stack::InputLayer;
+        use crossterm::event::KeyCode;
 
         tracing::trace!(
             "Editor.handle_key: code={:?}, modifiers={:?}",
@@ -38,152 +45,62 @@ impl Editor {
             modifiers
         );
 
-        // Create key event for dispatch methods
         let key_event = crossterm::event::KeyEvent::new(code, modifiers);
-
-        // Try terminal input dispatch first (handles terminal mode and re-entry)
-        if self.dispatch_terminal_input(&key_event).is_some() {
-            return Ok(());
-        }
-
-        // Clear skip_ensure_visible flag so cursor becomes visible after key press
-        // (scroll actions will set it again if needed)
-        let active_split = self.split_manager.active_split();
-        if let Some(view_state) = self.split_view_states.get_mut(&active_split) {
-            view_state.viewport.clear_skip_ensure_visible();
-        }
-
-        // Determine the current context first
-        let mut context = self.get_key_context();
-
-        // Special case: Hover and Signature Help popups should be dismissed on any key press
-        if matches!(context, crate::input::keybindings::KeyContext::Popup) {
-            // Check if the current popup is transient (hover, signature help)
-            let is_transient_popup = self
-                .active_state()
-                .popups
-                .top()
-                .is_some_and(|p| p.transient);
-
-            if is_transient_popup {
-                // Dismiss the popup on any key press
-                self.hide_popup();
-                tracing::debug!("Dismissed transient popup on key press");
-                // Recalculate context now that popup is gone
-                context = self.get_key_context();
-            }
-        }
-
-        // Try hierarchical modal input dispatch first (Settings, Menu, Prompt, Popup)
-        if self.dispatch_modal_input(&key_event).is_some() {
-            return Ok(());
-        }
-
-        // Only check buffer mode keybindings if we're not in a higher-priority context
-        // (Menu, Prompt, Popup should take precedence over mode bindings)
-        let should_check_mode_bindings = matches!(
-            context,
-            crate::input::keybindings::KeyContext::Normal
-                | crate::input::keybindings::KeyContext::FileExplorer
-        );
-
-        if should_check_mode_bindings {
-            // If we're in a global editor mode, handle chords and keybindings
-            if let Some(ref mode_name) = self.editor_mode {
-                // First, try to resolve as a chord (multi-key sequence like "gg")
-                if let Some(action_name) = self.mode_registry.resolve_chord_keybinding(
-                    mode_name,
-                    &self.chord_state,
-                    code,
-                    modifiers,
-                ) {
-                    tracing::debug!("Mode chord resolved to action: {}", action_name);
-                    self.chord_state.clear();
-                    let action = Action::from_str(&action_name, &std::collections::HashMap::new())
-                        .unwrap_or_else(|| Action::PluginAction(action_name));
-                    return self.handle_action(action);
-                }
-
-                // Check if this could be the start of a chord sequence
-                let is_potential_chord = self.mode_registry.is_chord_prefix(
-                    mode_name,
-                    &self.chord_state,
-                    code,
-                    modifiers,
-                );
-
-                if is_potential_chord {
-                    // This could be the start of a chord - add to state and wait
-                    tracing::debug!("Potential chord prefix in editor mode");
-                    self.chord_state.push((code, modifiers));
-                    return Ok(());
-                }
-
-                // Not a chord - clear any pending chord state
-                if !self.chord_state.is_empty() {
-                    tracing::debug!("Chord sequence abandoned in mode, clearing state");
-                    self.chord_state.clear();
+        let mut stack = self.focus_stack();
+        let mut layer = stack.top().cloned();
+        let mut cleared_skip_ensure_visible = false;
+        while let Some(current) = layer {
+            // Clear skip_ensure_visible flag so cursor becomes visible after key press
+            // (scroll actions will set it again if needed)
+            if !cleared_skip_ensure_visible
+                && !matches!(
+                    current,
+                    InputLayer::Notification | InputLayer::Hover | InputLayer::Terminal
+                )
+            {
+                let active_split = self.split_manager.active_split();
+                if let Some(view_state) = self.split_view_states.get_mut(&active_split) {
+                    view_state.viewport.clear_skip_ensure_visible();
                 }
+                cleared_skip_ensure_visible = true;
             }
 
-            // Check buffer mode keybindings (for virtual buffers with custom modes)
-            // Mode keybindings resolve to Action names (see Action::from_str)
-            if let Some(action_name) = self.resolve_mode_keybinding(code, modifiers) {
-                let action = Action::from_str(&action_name, &std::collections::HashMap::new())
-                    .unwrap_or_else(|| Action::PluginAction(action_name.clone()));
-                return self.handle_action(action);
-            }
-
-            // If we're in a global editor mode, check if we should block unbound keys
-            if let Some(ref mode_name) = self.editor_mode {
-                // Check if this mode is read-only
-                // read_only=true (like vi-normal): unbound keys should be ignored
-                // read_only=false (like vi-insert): unbound keys should insert characters
-                if self.mode_registry.is_read_only(mode_name) {
-                    tracing::debug!(
-                        "Ignoring unbound key in read-only mode {:?}",
-                        self.editor_mode
-                    );
-                    return Ok(());
-                }
-                // Mode is not read-only, fall through to normal key handling
-                tracing::debug!(
-                    "Mode {:?} is not read-only, allowing key through",
-                    self.editor_mode
-                );
+            if current.is_keymap() {
+                return self.dispatch_keymap_key(code, modifiers);
             }
-        }
 
-        // Check for chord sequence matches first
-        let key_event = crossterm::event::KeyEvent::new(code, modifiers);
-        let chord_result = self
-            .keybindings
-            .resolve_chord(&self.chord_state, &key_event, context);
-
-        match chord_result {
-            crate::input::keybindings::ChordResolution::Complete(action) => {
-                // Complete chord match - execute action and clear chord state
-                tracing::debug!("Complete chord match -> Action: {:?}", action);
-                self.chord_state.clear();
-                return self.handle_action(action);
-            }
-            crate::input::keybindings::ChordResolution::Partial => {
-                // Partial match - add to chord state and wait for more keys
-                tracing::debug!("Partial chord match - waiting for next key");
-                self.chord_state.push((code, modifiers));
+            // Escape closes the topmost layer that can be closed
+            let closed = code == KeyCode::Esc
+                && modifiers.is_empty()
+                && current.closes_on_escape()
+                && self.close_input_layer(&current);
+            if closed || self.offer_key_to_layer(&current, &key_event).is_consumed() {
+                self.note_key_layer(&key_event, current);
                 return Ok(());
             }
-            crate::input::keybindings::ChordResolution::NoMatch => {
-                // No chord match - clear state and try regular resolution
-                if !self.chord_state.is_empty() {
-                    tracing::debug!("Chord sequence abandoned, clearing state");
-                    self.chord_state.clear();
-                }
-            }
+
+            // The layer may have closed itself before passing the key on
+            stack = self.focus_stack();
+            layer = stack.next_below(current.priority()).cloned();
         }
+        Ok(())
+    }
 
-        // Regular single-key resolution
-        let action = self.keybindings.resolve(&key_event, context);
+    /// Resolve a key that reached the keymap layers and run its action
+    fn dispatch_keymap_key(
+        &mut self,
+        code: crossterm::event::KeyCode,
+        modifiers: crossterm::event::KeyModifiers,
+    ) -> std::io::Result<()> {
+        use crate::input::keybindings::Action;
+
+        let context = self.key_context;
+        let (resolution, layer) = self.resolve_key_in_context(context, code, modifiers);
+        self.note_key_layer(&crossterm::event::KeyEvent::new(code, modifiers), layer);
+        let action = match resolution {
+            KeyResolution::Action(action) => action,
+            KeyResolution::Pending | KeyResolution::Ignored => return Ok(()),
+        };
 
         tracing::trace!("Context: {:?} -> Action: {:?}", context, action);
 
@@ -203,22 +120,108 @@ impl Editor {
             }
         }
 
-        // Note: Modal components (Settings, Menu, Prompt, Popup, File Browser) are now
-        // handled by dispatch_modal_input using the InputHandler system.
-        // All remaining actions delegate to handle_action.
         self.handle_action(action)
     }
 
+    /// Resolve a key through the keymap context stack
+    ///
+    /// Mode bindings only apply to normal editing (Normal and FileExplorer);
+    /// higher-priority contexts go straight to the keybindings. Also returns
+    /// the input layer whose keymap claimed the key.
+    fn resolve_key_in_context(
+        &mut self,
+        context: crate::input::keybindings::KeyContext,
+        code: crossterm::event::KeyCode,
+        modifiers: crossterm::event::KeyModifiers,
+    ) -> (KeyResolution, crate::input::focus_stack::InputLayer) {
+        use crate::input::focus_stack::InputLayer;
+        use crate::input::keybindings::KeyContext;
+
+        let check_mode_bindings = matches!(context, KeyContext::Normal | KeyContext::FileExplorer);
+        let mut chord = std::mem::take(&mut self.chord_state);
+        let editor_mode = self
+            .editor_mode
+            .as_deref()
+            .filter(|_| check_mode_bindings)
+            .map(|mode| ModeLayer {
+                registry: &self.mode_registry,
+                mode,
+                chords: true,
+            });
+        let buffer_mode = self
+            .active_buffer_mode()
+            .filter(|_| check_mode_bindings)
+            .map(|mode| ModeLayer {
+                registry: &self.mode_registry,
+                mode,
+                chords: false,
+            });
+        let read_only = self
+            .editor_mode
+            .as_deref()
+            .filter(|_| check_mode_bindings)
+            .map(|mode| ReadOnlyModeLayer {
+                registry: &self.mode_registry,
+                mode,
+            });
+        let keybindings = KeybindingLayer {
+            resolver: &self.keybindings,
+            context,
+        };
+
+        let mut layers: Vec<&dyn KeymapLayer> = Vec::with_capacity(4);
+        let mut owners: Vec<InputLayer> = Vec::with_capacity(4);
+        if let Some(layer) = &editor_mode {
+            layers.push(layer);
+            owners.push(InputLayer::Mode(layer.mode.to_string()));
+        }
+        if let Some(layer) = &buffer_mode {
+            layers.push(layer);
+            owners.push(InputLayer::BufferMode(layer.mode.to_string()));
+        }
+        if let Some(layer) = &read_only {
+            // The read-only guard belongs to the editor mode
+            layers.push(layer);
+            owners.push(InputLayer::Mode(layer.mode.to_string()));
+        }
+        layers.push(&keybindings);
+        owners.push(InputLayer::Editor(context));
+
+        let (resolution, index) = resolve_key_with_layer(&layers, &mut chord, code, modifiers);
+        let owner = index
+            .and_then(|index| owners.get(index).cloned())
+            .unwrap_or(InputLayer::Editor(context));
+        self.chord_state = chord;
+        (resolution, owner)
+    }
+
     /// Handle an action (for normal mode and command execution)
+    ///
+    /// Runs the action through the pre-action hooks, macro recording and
+    /// post-action hooks; see [`crate::input::pipeline`].
     pub(super) fn handle_action(&mut self, action: Action) -> std::io::Result<()> {
+        let result = run_action(self, action).map(|_| ());
+        self.report_slow_clipboard();
+        result
+    }
+
+    /// Apply an action to the editor, without hooks or recording
+    fn perform_action(&mut self, action: Action) -> std::io::Result<()> {
         use crate::input::keybindings::Action;
 
-        // Record action to macro if recording
-        self.record_macro_action(&action);
+        self.stop_follow_tail_for_action(&action);
+
+        if self.split_lock_blocks(&action) {
+            return Ok(());
+        }
 
         match action {
             Action::Quit => self.quit(),
             Action::Save => {
+                // A buffer still loading (or that failed to) doesn't hold the file's contents
+                if !self.check_buffer_loaded(self.active_buffer()) {
+                    return Ok(());
+                }
                 // Check if buffer has a file path - if not, redirect to SaveAs
                 if self.active_state().buffer.file_path().is_none() {
                     self.start_prompt_with_initial_text(
@@ -226,6 +229,8 @@ impl Editor {
                         PromptType::SaveFileAs,
                         String::new(),
                     );
+                } else if self.ask_before_protected_save(self.active_buffer()) {
+                    // Saving a protected file waits for confirmation
                 } else if self.check_save_conflict().is_some() {
                     // Check if file was modified externally since we opened/saved it
                     self.start_prompt(
@@ -233,10 +238,28 @@ impl Editor {
                         PromptType::ConfirmSaveConflict,
                     );
                 } else {
-                    self.save()?;
+                    let buffer_id = self.active_buffer();
+                    self.refresh_file_writability(buffer_id);
+                    if let Err(e) = self.save_with_format() {
+                        // Failures for lack of permission, and any of remote
+                        // files, offer other ways to save
+                        let path = self
+                            .active_state()
+                            .buffer
+                            .file_path()
+                            .map(PathBuf::from)
+                            .unwrap_or_default();
+                        if reason_for_error(&e).is_none() && !is_remote_path(&path) {
+                            return Err(e);
+                        }
+                        self.report_failed_save(buffer_id, &path, e);
+                    }
                 }
             }
             Action::SaveAs => {
+                if !self.check_buffer_loaded(self.active_buffer()) {
+                    return Ok(());
+                }
                 // Get current filename as default suggestion
                 let current_path = self
                     .active_state()
@@ -268,10 +291,25 @@ impl Editor {
                 );
                 self.init_folder_open_state();
             }
+            Action::OpenRecentProject => self.start_recent_projects_prompt(),
+            Action::TogglePinProject => self.toggle_pin_project(),
             Action::GotoLine => self.start_prompt(
                 t!("file.goto_line_prompt").to_string(),
                 PromptType::GotoLine,
             ),
+            Action::GotoByteOffset => self.start_prompt(
+                t!("file.goto_byte_prompt").to_string(),
+                PromptType::GotoByteOffset,
+            ),
+            Action::GotoPercentage => self.start_prompt(
+                t!("file.goto_percentage_prompt").to_string(),
+                PromptType::GotoPercentage,
+            ),
+            Action::GotoDataPath => self.start_prompt(
+                t!("data_path.goto_prompt").to_string(),
+                PromptType::GotoDataPath,
+            ),
+            Action::CopyDataPath => self.copy_data_path(),
             Action::New => {
                 self.new_buffer();
             }
@@ -305,6 +343,39 @@ impl Editor {
             Action::CloseTab => {
                 self.close_tab();
             }
+            Action::RevertBufferToSaved => {
+                self.revert_buffer_to_saved();
+            }
+            Action::CompareBufferWith => {
+                self.start_compare_buffer_prompt();
+            }
+            Action::CompareNextHunk => {
+                self.compare_goto_hunk(true);
+            }
+            Action::ComparePreviousHunk => {
+                self.compare_goto_hunk(false);
+            }
+            Action::CompareCopyHunkToLeft => {
+                self.compare_copy_hunk(true);
+            }
+            Action::CompareCopyHunkToRight => {
+                self.compare_copy_hunk(false);
+            }
+            Action::CompareWithClipboard => {
+                self.compare_with_clipboard();
+            }
+            Action::CompareDirectories => {
+                self.start_compare_directories_prompt();
+            }
+            Action::DirCompareOpen => {
+                self.dir_compare_open();
+            }
+            Action::DirCompareToggleIdentical => {
+                self.toggle_dir_compare_identical();
+            }
+            Action::DirCompareRefresh => {
+                self.refresh_dir_compare();
+            }
             Action::Revert => {
                 // Check if buffer has unsaved changes - prompt for confirmation
                 if self.active_state().buffer.is_modified() {
@@ -331,6 +402,16 @@ impl Editor {
             Action::ToggleAutoRevert => {
                 self.toggle_auto_revert();
             }
+            Action::ToggleBufferAutoRevert => {
+                self.toggle_buffer_auto_revert();
+            }
+            Action::ToggleFollowTail => {
+                self.toggle_follow_tail();
+            }
+            Action::TogglePinBuffer => self.toggle_pin_buffer(),
+            Action::EditAnyway => self.edit_anyway(),
+            Action::UnlockProtectedFile => self.unlock_protected_file(),
+            Action::CloseSavedBuffers => self.close_saved_buffers(),
             Action::FormatBuffer => {
                 if let Err(e) = self.format_buffer() {
                     self.set_status_message(
@@ -338,22 +419,46 @@ impl Editor {
                     );
                 }
             }
+            Action::RunSaveActions => self.run_save_actions(false)?,
             Action::Copy => self.copy_selection(),
             Action::CopyWithTheme(theme) => self.copy_selection_with_theme(&theme),
             Action::Cut => {
-                if self.is_editing_disabled() {
-                    self.set_status_message(t!("buffer.editing_disabled").to_string());
+                if self.edit_blocked(&Action::Cut) {
                     return Ok(());
                 }
                 self.cut_selection()
             }
             Action::Paste => {
-                if self.is_editing_disabled() {
-                    self.set_status_message(t!("buffer.editing_disabled").to_string());
+                if self.edit_blocked(&Action::Paste) {
                     return Ok(());
                 }
                 self.paste()
             }
+            Action::PasteReindent => {
+                if self.edit_blocked(&Action::PasteReindent) {
+                    return Ok(());
+                }
+                self.paste_reindented()
+            }
+            Action::PasteVerbatim => {
+                if self.edit_blocked(&Action::PasteVerbatim) {
+                    return Ok(());
+                }
+                self.paste_verbatim()
+            }
+            Action::PasteFromHistory => {
+                if self.edit_blocked(&Action::PasteFromHistory) {
+                    return Ok(());
+                }
+                self.start_paste_from_history_prompt()
+            }
+            Action::CyclePaste => {
+                if self.edit_blocked(&Action::CyclePaste) {
+                    return Ok(());
+                }
+                self.cycle_paste()
+            }
+            Action::RetrySystemClipboard => self.retry_system_clipboard(),
             Action::YankWordForward => self.yank_word_forward(),
             Action::YankWordBackward => self.yank_word_backward(),
             Action::YankToLineEnd => self.yank_to_line_end(),
@@ -370,15 +475,64 @@ impl Editor {
             Action::ShowKeyboardShortcuts => {
                 self.open_keyboard_shortcuts();
             }
+            Action::KeyboardShortcutsFilter => {
+                self.start_keyboard_shortcuts_filter();
+            }
             Action::ShowWarnings => {
                 self.show_warnings_popup();
             }
+            Action::ReviewBlockedCommands => {
+                self.review_blocked_commands();
+            }
+            Action::CancelProgress => self.cancel_newest_progress(),
             Action::ShowLspStatus => {
                 self.show_lsp_status_popup();
             }
+            Action::UpdateNow => {
+                self.start_self_update();
+            }
+            Action::ShowSessionOwnership => {
+                self.show_session_ownership();
+            }
             Action::ClearWarnings => {
                 self.clear_warnings();
             }
+            Action::FocusNotifications => {
+                self.focus_notifications();
+            }
+            Action::ShowNotificationHistory => {
+                self.show_notification_history();
+            }
+            Action::ShowUsageStats => {
+                self.show_usage_stats();
+            }
+            Action::ExportUsageStats => {
+                self.start_export_usage_stats_prompt();
+            }
+            Action::ExportSettingsArchive => {
+                self.show_settings_export_popup(0);
+            }
+            Action::ImportSettingsArchive => {
+                self.start_settings_import_prompt();
+            }
+            Action::ShowFullMessage => {
+                self.show_full_message();
+            }
+            Action::SelectionStatistics => {
+                self.show_selection_statistics();
+            }
+            Action::OpenEditorLog => {
+                self.open_editor_log();
+            }
+            Action::FilterEditorLog => {
+                self.start_editor_log_level_prompt();
+            }
+            Action::OpenEditorLogAtEntry => {
+                self.open_editor_log_at_entry();
+            }
+            Action::RetryPluginInit => {
+                self.retry_plugin_init();
+            }
             Action::CommandPalette => {
                 // Toggle command palette: close if already open, otherwise open it
                 if let Some(prompt) = &self.prompt {
@@ -420,6 +574,24 @@ impl Editor {
             Action::ToggleComposeMode => {
                 self.handle_toggle_compose_mode();
             }
+            Action::ToggleDistractionFree => {
+                self.toggle_distraction_free();
+            }
+            Action::RestoreSessionSnapshot => {
+                self.start_restore_session_snapshot_prompt();
+            }
+            Action::SaveLayoutAs => self.start_save_layout_prompt(),
+            Action::ScrollUpKeepCursor => self.scroll_keeping_cursor(-1),
+            Action::ScrollDownKeepCursor => self.scroll_keeping_cursor(1),
+            Action::ScrollHalfPageUp => self.scroll_half_page(-1),
+            Action::ScrollHalfPageDown => self.scroll_half_page(1),
+            Action::CursorLineToTop => self.place_cursor_line(LinePlacement::Top),
+            Action::CursorLineToCenter => self.place_cursor_line(LinePlacement::Center),
+            Action::CursorLineToBottom => self.place_cursor_line(LinePlacement::Bottom),
+            Action::SwitchLayout(name) if name.is_empty() => self.start_switch_layout_prompt(),
+            Action::SwitchLayout(name) => self.switch_layout(&name),
+            Action::SwitchLayoutAt(index) => self.switch_layout_at(index),
+            Action::DeleteLayout => self.start_delete_layout_prompt(),
             Action::SetComposeWidth => {
                 let active_split = self.split_manager.active_split();
                 let current = self
@@ -459,6 +631,7 @@ impl Editor {
                 );
             }
             Action::LspCompletion => {
+                self.cancel_auto_completion();
                 self.request_completion()?;
             }
             Action::LspGotoDefinition => {
@@ -467,6 +640,9 @@ impl Editor {
             Action::LspRename => {
                 self.start_rename()?;
             }
+            Action::RenameInBuffer => {
+                self.start_rename_in_buffer();
+            }
             Action::LspHover => {
                 self.request_hover()?;
             }
@@ -479,6 +655,9 @@ impl Editor {
             Action::LspCodeActions => {
                 self.request_code_actions()?;
             }
+            Action::BatchCodeAction => {
+                self.start_batch_code_action_prompt();
+            }
             Action::LspRestart => {
                 self.handle_lsp_restart();
             }
@@ -488,12 +667,30 @@ impl Editor {
             Action::ToggleInlayHints => {
                 self.toggle_inlay_hints();
             }
+            Action::ToggleInlineBlame => {
+                self.toggle_inline_blame(false);
+            }
+            Action::ToggleInlineBlameAll => {
+                self.toggle_inline_blame(true);
+            }
+            Action::ShowBlameCommit => {
+                self.show_blame_commit();
+            }
             Action::DumpConfig => {
                 self.dump_config();
             }
             Action::SelectTheme => {
                 self.start_select_theme_prompt();
             }
+            Action::ReloadTheme => {
+                self.reload_theme();
+            }
+            Action::ToggleLightDarkTheme => {
+                self.toggle_light_dark_theme();
+            }
+            Action::CheckThemeContrast => {
+                self.show_theme_contrast();
+            }
             Action::SelectKeybindingMap => {
                 self.start_select_keybinding_map_prompt();
             }
@@ -560,6 +757,9 @@ impl Editor {
             Action::FindSelectionPrevious => {
                 self.find_selection_previous();
             }
+            Action::SearchInAllSplits => {
+                self.search_in_all_splits();
+            }
             Action::AddCursorNextMatch => self.add_cursor_at_next_match(),
             Action::AddCursorAbove => self.add_cursor_above(),
             Action::AddCursorBelow => self.add_cursor_below(),
@@ -605,12 +805,19 @@ impl Editor {
             Action::IncreaseSplitSize => self.adjust_split_size(0.05),
             Action::DecreaseSplitSize => self.adjust_split_size(-0.05),
             Action::ToggleMaximizeSplit => self.toggle_maximize_split(),
+            Action::TogglePaneLock => self.toggle_split_lock(),
             Action::ToggleFileExplorer => self.toggle_file_explorer(),
             Action::ToggleMenuBar => self.toggle_menu_bar(),
             Action::ToggleLineNumbers => self.toggle_line_numbers(),
             Action::ToggleMouseCapture => self.toggle_mouse_capture(),
             Action::ToggleMouseHover => self.toggle_mouse_hover(),
             Action::ToggleDebugHighlights => self.toggle_debug_highlights(),
+            Action::ToggleInputLatencyHud => self.toggle_input_latency_hud(),
+            Action::ShowInputLayers => self.show_input_layers(),
+            Action::FreeCaches => self.free_caches(),
+            Action::CompactBuffer => self.start_compact_buffers_prompt(false),
+            Action::CompactAllBuffers => self.start_compact_buffers_prompt(true),
+            Action::BufferMemoryReport => self.show_buffer_memory_report(),
             // Buffer settings
             Action::SetTabSize => {
                 let current = self
@@ -649,6 +856,8 @@ impl Editor {
                     self.set_status_message(status.to_string());
                 }
             }
+            Action::ToggleRenderWhitespace => self.toggle_render_whitespace(),
+            Action::ToggleAnsiColors => self.toggle_ansi_colors(),
             Action::ResetBufferSettings => self.reset_buffer_settings(),
             Action::FocusFileExplorer => self.focus_file_explorer(),
             Action::FocusEditor => self.focus_editor(),
@@ -666,6 +875,10 @@ impl Editor {
             Action::FileExplorerRename => self.file_explorer_rename(),
             Action::FileExplorerToggleHidden => self.file_explorer_toggle_hidden(),
             Action::FileExplorerToggleGitignored => self.file_explorer_toggle_gitignored(),
+            Action::FileExplorerToggleDetails => self.file_explorer_toggle_details(),
+            Action::FileExplorerCycleSort => self.file_explorer_cycle_sort(),
+            Action::FileExplorerShowAll => self.file_explorer_show_all_entries(),
+            Action::FileExplorerContextMenu => self.open_explorer_context_menu_at_selection(),
             Action::RemoveSecondaryCursors => {
                 // Convert action to events and apply them
                 if let Some(events) = self.action_to_events(Action::RemoveSecondaryCursors) {
@@ -717,6 +930,9 @@ impl Editor {
             Action::MenuOpen(menu_name) => {
                 self.handle_menu_open(&menu_name);
             }
+            Action::RunCommand(name, args) => {
+                return self.run_command_with_args(&name, &args);
+            }
 
             Action::SwitchKeybindingMap(map_name) => {
                 // Check if the map exists (either built-in or user-defined)
@@ -770,15 +986,7 @@ impl Editor {
                 self.list_bookmarks();
             }
             Action::ToggleSearchCaseSensitive => {
-                self.search_case_sensitive = !self.search_case_sensitive;
-                let state = if self.search_case_sensitive {
-                    "enabled"
-                } else {
-                    "disabled"
-                };
-                self.set_status_message(
-                    t!("search.case_sensitive_state", state = state).to_string(),
-                );
+                self.toggle_search_case_sensitive();
                 // Update incremental highlights if in search prompt, otherwise re-run completed search
                 // Check prompt FIRST since we want to use current prompt input, not stale search_state
                 if let Some(prompt) = &self.prompt {
@@ -791,7 +999,7 @@ impl Editor {
                         let query = prompt.input.clone();
                         self.update_search_highlights(&query);
                     }
-                } else if let Some(search_state) = &self.search_state {
+                } else if let Some(search_state) = self.active_search() {
                     let query = search_state.query.clone();
                     self.perform_search(&query);
                 }
@@ -816,7 +1024,7 @@ impl Editor {
                         let query = prompt.input.clone();
                         self.update_search_highlights(&query);
                     }
-                } else if let Some(search_state) = &self.search_state {
+                } else if let Some(search_state) = self.active_search() {
                     let query = search_state.query.clone();
                     self.perform_search(&query);
                 }
@@ -841,7 +1049,7 @@ impl Editor {
                         let query = prompt.input.clone();
                         self.update_search_highlights(&query);
                     }
-                } else if let Some(search_state) = &self.search_state {
+                } else if let Some(search_state) = self.active_search() {
                     let query = search_state.query.clone();
                     self.perform_search(&query);
                 }
@@ -904,8 +1112,7 @@ impl Editor {
             }
             Action::None => {}
             Action::DeleteBackward => {
-                if self.is_editing_disabled() {
-                    self.set_status_message(t!("buffer.editing_disabled").to_string());
+                if self.edit_blocked(&Action::DeleteBackward) {
                     return Ok(());
                 }
                 // Normal backspace handling
@@ -913,7 +1120,8 @@ impl Editor {
                     if events.len() > 1 {
                         // Multi-cursor: use optimized bulk edit (O(n) instead of O(n²))
                         let description = "Delete backward".to_string();
-                        if let Some(bulk_edit) = self.apply_events_as_bulk_edit(events, description)
+                        if let Some(bulk_edit) =
+                            self.apply_cursor_events_as_bulk_edit(events, description)
                         {
                             self.active_event_log_mut().append(bulk_edit);
                         }
@@ -929,7 +1137,15 @@ impl Editor {
                 // Execute the plugin callback via TypeScript plugin thread
                 // Use non-blocking version to avoid deadlock with async plugin ops
                 #[cfg(feature = "plugins")]
-                if let Some(result) = self.plugin_manager.execute_action_async(&action_name) {
+                let options = crate::services::plugins::api::ActionOptions {
+                    dry_run: self.dry_run,
+                    args: self.command_args.take(),
+                };
+                #[cfg(feature = "plugins")]
+                if let Some(result) = self
+                    .plugin_manager
+                    .execute_action_with_options_async(&action_name, options)
+                {
                     match result {
                         Ok(receiver) => {
                             // Store pending action for processing in main loop
@@ -1008,6 +1224,30 @@ impl Editor {
                 // Run shell command on buffer/selection, replace content
                 self.start_shell_command_prompt(true);
             }
+            Action::InsertFile => self.start_insert_file_prompt(),
+            Action::InsertCommandOutput => self.start_insert_command_output_prompt(),
+            Action::ToggleCommandStdin => self.toggle_command_output_stdin(),
+            Action::ToTitleCase
+            | Action::ToCamelCase
+            | Action::ToSnakeCase
+            | Action::SortLines
+            | Action::UniqueLines
+            | Action::UniqueAdjacentLines
+            | Action::ReverseLines
+            | Action::ShuffleLines => self.handle_text_transform_action(action),
+            Action::PasteAsColumn => self.paste_as_column(),
+            Action::AlignSelections => self.start_align_selections_prompt(),
+            Action::PendingEditsToggle => self.pending_edits_toggle(),
+            Action::PendingEditsToggleFile => self.pending_edits_toggle_file(),
+            Action::PendingEditsToggleExpand => self.pending_edits_toggle_expand(),
+            Action::PendingEditsApply => self.pending_edits_apply(),
+            Action::PendingEditsCancel => self.pending_edits_cancel(),
+            Action::TodosList => self.list_todos(),
+            Action::TodosRefresh => self.refresh_todos(),
+            Action::TodosOpen => self.todos_open(),
+            Action::WorkspaceDiagnostics => self.open_workspace_diagnostics(),
+            Action::WorkspaceDiagnosticsOpen => self.workspace_diagnostics_open(),
+            Action::RemoteDirOpen => self.remote_dir_open(),
             Action::OpenSettings => {
                 self.open_settings();
             }
@@ -1065,6 +1305,28 @@ impl Editor {
                         PromptResult::ExecuteAction(action) => {
                             return self.handle_action(action);
                         }
+                        PromptResult::ExecuteActionWithArgs(action, args) => {
+                            return self.handle_action_with_args(action, args, false);
+                        }
+                        PromptResult::EarlyReturn => {
+                            return Ok(());
+                        }
+                        PromptResult::Done => {}
+                    }
+                }
+            }
+            Action::PromptConfirmDryRun => {
+                // Like PromptConfirm, but a command chosen in the palette
+                // runs as a dry run
+                if let Some((input, prompt_type, selected_index)) = self.confirm_prompt() {
+                    use super::prompt_actions::PromptResult;
+                    match self.handle_prompt_confirm_input(input, prompt_type, selected_index) {
+                        PromptResult::ExecuteAction(action) => {
+                            return self.handle_action_dry_run(action);
+                        }
+                        PromptResult::ExecuteActionWithArgs(action, args) => {
+                            return self.handle_action_with_args(action, args, true);
+                        }
                         PromptResult::EarlyReturn => {
                             return Ok(());
                         }
@@ -1164,8 +1426,12 @@ impl Editor {
                         return Ok(());
                     }
 
-                    // Get current selected index
-                    let current_index = explorer.get_selected_index().unwrap_or(0);
+                    // Get current selected index (footer rows can't be selected,
+                    // so work in node indices)
+                    let current_index = explorer
+                        .get_selected()
+                        .and_then(|selected| visible.iter().position(|&id| id == selected))
+                        .unwrap_or(0);
 
                     // Calculate new index based on scroll delta
                     let new_index = if delta < 0 {
@@ -1177,32 +1443,30 @@ impl Editor {
                     };
 
                     // Set the new selection
-                    if let Some(node_id) = explorer.get_node_at_index(new_index) {
-                        explorer.set_selected(Some(node_id));
-                        explorer.update_scroll_for_selection();
-                    }
+                    explorer.set_selected(Some(visible[new_index]));
+                    explorer.update_scroll_for_selection();
                 }
                 return Ok(());
             }
         }
 
-        // Otherwise, scroll the editor in the active split
+        // Otherwise, scroll the editor in the split under the mouse
         // Use SplitViewState's viewport (View events go to SplitViewState, not EditorState)
-        let active_split = self.split_manager.active_split();
+        let (split_id, buffer_id) = self.scroll_target_split(col, row);
+        if delta < 0 {
+            self.stop_follow_tail(buffer_id);
+        }
 
         // Get view_transform tokens from SplitViewState (if any)
         let view_transform_tokens = self
             .split_view_states
-            .get(&active_split)
+            .get(&split_id)
             .and_then(|vs| vs.view_transform.as_ref())
             .map(|vt| vt.tokens.clone());
 
         // Get mutable references to both buffer and view state
-        let buffer = self
-            .buffers
-            .get_mut(&self.active_buffer())
-            .map(|s| &mut s.buffer);
-        let view_state = self.split_view_states.get_mut(&active_split);
+        let buffer = self.buffers.get_mut(&buffer_id).map(|s| &mut s.buffer);
+        let view_state = self.split_view_states.get_mut(&split_id);
 
         if let (Some(buffer), Some(view_state)) = (buffer, view_state) {
             let top_byte_before = view_state.viewport.top_byte;
@@ -1240,6 +1504,47 @@ impl Editor {
         Ok(())
     }
 
+    /// Handle horizontal mouse wheel / trackpad scroll (negative delta scrolls left)
+    ///
+    /// Scrolls the split under the mouse; does nothing when line wrapping is on.
+    pub(super) fn handle_mouse_horizontal_scroll(&mut self, col: u16, row: u16, delta: isize) {
+        // Keep the viewport in sync with keyboard navigation (see handle_mouse_scroll)
+        self.sync_editor_state_to_split_view_state();
+
+        let (split_id, buffer_id) = self.scroll_target_split(col, row);
+        let buffer = self.buffers.get_mut(&buffer_id).map(|s| &mut s.buffer);
+        let view_state = self.split_view_states.get_mut(&split_id);
+
+        if let (Some(buffer), Some(view_state)) = (buffer, view_state) {
+            let left_before = view_state.viewport.left_column;
+            view_state.viewport.scroll_horizontal(buffer, delta);
+            // Skip ensure_visible so the cursor doesn't pull the view back
+            view_state.viewport.set_skip_ensure_visible();
+            tracing::trace!(
+                "handle_mouse_horizontal_scroll: delta={}, left_column {} -> {}",
+                delta,
+                left_before,
+                view_state.viewport.left_column
+            );
+        }
+    }
+
+    /// The split a mouse scroll at (col, row) applies to: the split under the
+    /// mouse, or the active split if the mouse isn't over any split's content
+    fn scroll_target_split(&self, col: u16, row: u16) -> (SplitId, BufferId) {
+        self.cached_layout
+            .split_areas
+            .iter()
+            .find(|(_, _, content_rect, _, _, _)| {
+                col >= content_rect.x
+                    && col < content_rect.x + content_rect.width
+                    && row >= content_rect.y
+                    && row < content_rect.y + content_rect.height
+            })
+            .map(|(split_id, buffer_id, _, _, _, _)| (*split_id, *buffer_id))
+            .unwrap_or_else(|| (self.split_manager.active_split(), self.active_buffer()))
+    }
+
     /// Handle scrollbar drag with relative movement (when dragging from thumb)
     pub(super) fn handle_scrollbar_drag_relative(
         &mut self,
@@ -1710,6 +2015,9 @@ impl Editor {
             .map(|vs| vs.viewport.top_byte)
             .unwrap_or(0);
 
+        // Gutter offset and position of a click in the gutter
+        let mut gutter_click = None;
+
         // Calculate clicked position in buffer
         if let Some(state) = self.buffers.get_mut(&buffer_id) {
             let gutter_width = state.margins.left_total_width() as u16;
@@ -1780,6 +2088,18 @@ impl Editor {
             self.mouse_state.dragging_text_selection = true;
             self.mouse_state.drag_selection_split = Some(split_id);
             self.mouse_state.drag_selection_anchor = Some(target_position);
+
+            let gutter_x = col.saturating_sub(content_rect.x);
+            if gutter_x < gutter_width {
+                gutter_click = Some((gutter_x as usize, target_position));
+            }
+        }
+
+        // Tell plugins which gutter column was clicked
+        if let Some((x, position)) = gutter_click {
+            if let Some((column, line)) = self.gutter_click_target(buffer_id, x, position) {
+                self.fire_gutter_clicked(buffer_id, line, column);
+            }
         }
 
         Ok(())
@@ -1811,12 +2131,25 @@ impl Editor {
         let relative_row = row.saturating_sub(explorer_area.y + 1); // +1 for top border
 
         if let Some(ref mut explorer) = self.file_explorer {
-            let display_nodes = explorer.get_display_nodes();
+            // The header row above the entries isn't an entry
+            let Some(relative_row) = relative_row.checked_sub(explorer.header_rows() as u16) else {
+                return Ok(());
+            };
+            let display_rows = explorer.get_display_rows();
             let scroll_offset = explorer.get_scroll_offset();
             let clicked_index = (relative_row as usize) + scroll_offset;
 
-            if clicked_index < display_nodes.len() {
-                let (node_id, _indent) = display_nodes[clicked_index];
+            if clicked_index < display_rows.len() {
+                let node_id = match display_rows[clicked_index].0 {
+                    TreeRow::Node(node_id) => node_id,
+                    TreeRow::MoreEntries { dir, .. } => {
+                        // Clicking the "more not shown" row loads the rest
+                        explorer.set_selected(Some(dir));
+                        self.file_explorer_show_all_entries();
+                        return Ok(());
+                    }
+                    TreeRow::Loading(_) => return Ok(()),
+                };
 
                 // Select this node
                 explorer.set_selected(Some(node_id));
@@ -1894,7 +2227,8 @@ impl Editor {
 
     /// Start the theme selection prompt with available themes
     fn start_select_theme_prompt(&mut self) {
-        let available_themes = crate::view::theme::Theme::available_themes();
+        let available_themes =
+            crate::view::theme::Theme::available_themes_in(Some(&self.dir_context.themes_dir()));
         let current_theme_name = &self.theme.name;
 
         // Find the index of the current theme
@@ -1941,10 +2275,12 @@ impl Editor {
     /// Apply a theme by name and persist it to config
     pub(super) fn apply_theme(&mut self, theme_name: &str) {
         if !theme_name.is_empty() {
-            self.theme = crate::view::theme::Theme::from_name(theme_name);
+            self.theme = self.load_theme(theme_name);
+            self.apply_glyphs();
 
             // Update the config in memory
             self.config.theme = self.theme.name.clone().into();
+            self.theme_changed();
 
             // Persist to config file
             self.save_theme_to_config();
@@ -1957,15 +2293,7 @@ impl Editor {
 
     /// Save the current theme setting to the user's config file
     fn save_theme_to_config(&mut self) {
-        // Create the directory if it doesn't exist
-        if let Err(e) = std::fs::create_dir_all(&self.dir_context.config_dir) {
-            tracing::warn!("Failed to create config directory: {}", e);
-            return;
-        }
-
-        // Save the config
-        let config_path = self.dir_context.config_path();
-        if let Err(e) = self.config.save_to_file(&config_path) {
+        if let Err(e) = self.persist_config(ConfigLayer::User) {
             tracing::warn!("Failed to save theme to config: {}", e);
         }
     }
@@ -2062,15 +2390,7 @@ impl Editor {
 
     /// Save the current keybinding map setting to the user's config file
     fn save_keybinding_map_to_config(&mut self) {
-        // Create the directory if it doesn't exist
-        if let Err(e) = std::fs::create_dir_all(&self.dir_context.config_dir) {
-            tracing::warn!("Failed to create config directory: {}", e);
-            return;
-        }
-
-        // Save the config
-        let config_path = self.dir_context.config_path();
-        if let Err(e) = self.config.save_to_file(&config_path) {
+        if let Err(e) = self.persist_config(ConfigLayer::User) {
             tracing::warn!("Failed to save keybinding map to config: {}", e);
         }
     }
@@ -2128,13 +2448,10 @@ impl Editor {
         use crate::config::CursorStyle;
 
         if let Some(style) = CursorStyle::from_str(style_name) {
-            // Update the config in memory
+            // Update the config in memory; the event loop applies it to the
+            // terminal unless a ui.cursor style takes precedence
             self.config.editor.cursor_style = style;
 
-            // Apply the cursor style to the terminal
-            use std::io::stdout;
-            let _ = crossterm::execute!(stdout(), style.to_crossterm_style());
-
             // Persist to config file
             self.save_cursor_style_to_config();
 
@@ -2154,15 +2471,7 @@ impl Editor {
 
     /// Save the current cursor style setting to the user's config file
     fn save_cursor_style_to_config(&mut self) {
-        // Create the directory if it doesn't exist
-        if let Err(e) = std::fs::create_dir_all(&self.dir_context.config_dir) {
-            tracing::warn!("Failed to create config directory: {}", e);
-            return;
-        }
-
-        // Save the config
-        let config_path = self.dir_context.config_path();
-        if let Err(e) = self.config.save_to_file(&config_path) {
+        if let Err(e) = self.persist_config(ConfigLayer::User) {
             tracing::warn!("Failed to save cursor style to config: {}", e);
         }
     }
@@ -2265,15 +2574,7 @@ impl Editor {
 
     /// Save the current locale setting to the user's config file
     fn save_locale_to_config(&mut self) {
-        // Create the directory if it doesn't exist
-        if let Err(e) = std::fs::create_dir_all(&self.dir_context.config_dir) {
-            tracing::warn!("Failed to create config directory: {}", e);
-            return;
-        }
-
-        // Save the config
-        let config_path = self.dir_context.config_path();
-        if let Err(e) = self.config.save_to_file(&config_path) {
+        if let Err(e) = self.persist_config(ConfigLayer::User) {
             tracing::warn!("Failed to save locale to config: {}", e);
         }
     }
@@ -2327,6 +2628,10 @@ impl Editor {
             return;
         }
 
+        // Pinned buffers first, each group in tab order
+        let mut open_buffers = open_buffers;
+        open_buffers.sort_by_key(|&id| !self.is_buffer_pinned(id));
+
         // Find the current buffer's index
         let current_index = open_buffers
             .iter()
@@ -2336,11 +2641,15 @@ impl Editor {
         let suggestions: Vec<crate::input::commands::Suggestion> = open_buffers
             .iter()
             .map(|&buffer_id| {
-                let display_name = self
+                let mut display_name = self
                     .buffer_metadata
                     .get(&buffer_id)
                     .map(|m| m.display_name.clone())
                     .unwrap_or_else(|| format!("Buffer {:?}", buffer_id));
+                if self.is_buffer_pinned(buffer_id) {
+                    display_name =
+                        format!("{} {}", crate::view::glyphs::glyphs().pinned, display_name);
+                }
 
                 let is_current = buffer_id == self.active_buffer();
                 let is_modified = self
@@ -2442,9 +2751,8 @@ impl Editor {
 
     /// Handle character insertion in normal editor mode.
     fn handle_insert_char_editor(&mut self, c: char) -> std::io::Result<()> {
-        // Check if editing is disabled (show_cursors = false)
-        if self.is_editing_disabled() {
-            self.set_status_message(t!("buffer.editing_disabled").to_string());
+        // Check if editing is disabled (show_cursors = false) or the buffer is still loading
+        if self.edit_blocked(&Action::InsertChar(c)) {
             return Ok(());
         }
 
@@ -2455,7 +2763,7 @@ impl Editor {
             if events.len() > 1 {
                 // Multi-cursor: use optimized bulk edit (O(n) instead of O(n²))
                 let description = format!("Insert '{}'", c);
-                if let Some(bulk_edit) = self.apply_events_as_bulk_edit(events, description.clone())
+                if let Some(bulk_edit) = self.apply_cursor_events_as_bulk_edit(events, description)
                 {
                     self.active_event_log_mut().append(bulk_edit);
                 }
@@ -2473,6 +2781,9 @@ impl Editor {
             let _ = self.request_signature_help();
         }
 
+        self.request_on_type_formatting(c);
+        self.auto_complete_after_typing(c);
+
         Ok(())
     }
 
@@ -2484,6 +2795,7 @@ impl Editor {
     fn apply_action_as_events(&mut self, action: Action) -> std::io::Result<()> {
         // Get description before moving action
         let action_description = format!("{:?}", action);
+        let is_newline = matches!(action, Action::InsertNewline);
 
         // Check if this is an editing action and editing is disabled
         let is_editing_action = matches!(
@@ -2494,15 +2806,27 @@ impl Editor {
                 | Action::DeleteWordBackward
                 | Action::DeleteWordForward
                 | Action::DeleteLine
+                | Action::DeleteParagraphForward
+                | Action::DeleteParagraphBackward
+                | Action::DeleteSentenceForward
+                | Action::DeleteSentenceBackward
                 | Action::DedentSelection
                 | Action::ToggleComment
         );
 
-        if is_editing_action && self.is_editing_disabled() {
-            self.set_status_message(t!("buffer.editing_disabled").to_string());
+        if is_editing_action && self.edit_blocked(&action) {
             return Ok(());
         }
 
+        // Indenting and dedenting selected lines are named in undo messages
+        let is_indent = matches!(action, Action::InsertTab)
+            && self
+                .active_state()
+                .cursors
+                .iter()
+                .any(|(_, cursor)| cursor.selection_range().is_some());
+        let is_dedent = matches!(action, Action::DedentSelection);
+
         if let Some(events) = self.action_to_events(action) {
             if events.len() > 1 {
                 // Check if this batch contains buffer modifications
@@ -2511,11 +2835,35 @@ impl Editor {
                     .any(|e| matches!(e, Event::Insert { .. } | Event::Delete { .. }));
 
                 if has_buffer_mods {
-                    // Multi-cursor buffer edit: use optimized bulk edit (O(n) instead of O(n²))
-                    if let Some(bulk_edit) =
+                    let label = if is_indent {
+                        let count = events
+                            .iter()
+                            .filter(|e| matches!(e, Event::Insert { .. }))
+                            .count();
+                        Some(t!("undo.indent_lines", count = count).to_string())
+                    } else if is_dedent {
+                        let count = events
+                            .iter()
+                            .filter(|e| matches!(e, Event::Delete { .. }))
+                            .count();
+                        Some(t!("undo.dedent_lines", count = count).to_string())
+                    } else {
+                        None
+                    };
+                    // Multi-cursor buffer edit: use optimized bulk edit (O(n) instead of O(n²)).
+                    // Indenting and dedenting move every cursor to where it
+                    // ends up, rather than following edits of its own.
+                    let bulk_edit = if is_indent || is_dedent {
                         self.apply_events_as_bulk_edit(events.clone(), action_description)
-                    {
-                        self.active_event_log_mut().append(bulk_edit);
+                    } else {
+                        self.apply_cursor_events_as_bulk_edit(events.clone(), action_description)
+                    };
+                    if let Some(bulk_edit) = bulk_edit {
+                        let event_log = self.active_event_log_mut();
+                        match label {
+                            Some(label) => event_log.append_labeled(bulk_edit, label),
+                            None => event_log.append(bulk_edit),
+                        };
                     }
                 } else {
                     // Multi-cursor non-buffer operation: use Batch for atomic undo
@@ -2539,6 +2887,10 @@ impl Editor {
                     self.track_cursor_movement(&event);
                 }
             }
+
+            if is_newline {
+                self.request_on_type_formatting('\n');
+            }
         }
 
         Ok(())
@@ -2561,3 +2913,43 @@ impl Editor {
         }
     }
 }
+
+impl ActionHost for Editor {
+    fn before_action(&mut self, action: &Action) -> bool {
+        let args = HookArgs::PreCommand {
+            action: action.clone(),
+        };
+        if !self.action_hooks.run_hooks("pre_command", &args) {
+            return false;
+        }
+        if self.plugin_manager.has_hook_handlers("pre_command") {
+            self.plugin_manager.run_hook("pre_command", args);
+        }
+        true
+    }
+
+    fn record_action(&mut self, action: &Action) {
+        self.record_macro_action(action);
+    }
+
+    fn execute_action(&mut self, action: Action) -> std::io::Result<()> {
+        self.perform_action(action)
+    }
+
+    fn after_action(&mut self, action: &Action) {
+        self.record_usage_action(action);
+        let dropped = self.active_state_mut().buffer.take_dropped_invalid_bytes();
+        if dropped > 0 {
+            self.set_status_message(
+                t!("buffer.invalid_bytes_dropped", count = dropped).to_string(),
+            );
+        }
+        let args = HookArgs::PostCommand {
+            action: action.clone(),
+        };
+        self.action_hooks.run_hooks("post_command", &args);
+        if self.plugin_manager.has_hook_handlers("post_command") {
+            self.plugin_manager.run_hook("post_command", args);
+        }
+    }
+}
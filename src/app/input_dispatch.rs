@@ -1,12 +1,15 @@
 //! Input dispatch using the hierarchical InputHandler system.
 //!
-//! This module provides the bridge between Editor and the InputHandler trait,
-//! dispatching input to modal components and processing deferred actions.
+//! This module provides the bridge between Editor and the InputHandler trait:
+//! it builds the input focus stack (see [`crate::input::focus_stack`]),
+//! offers keys to the surface behind each layer and processes deferred
+//! actions.
 
 use super::terminal_input::{should_enter_terminal_mode, TerminalModeInputHandler};
 use super::Editor;
+use crate::input::focus_stack::{FocusStack, InputLayer};
 use crate::input::handler::{DeferredAction, InputContext, InputHandler, InputResult};
-use crate::input::keybindings::Action;
+use crate::input::keybindings::{format_keybinding, Action, KeyContext};
 use crate::view::file_browser_input::FileBrowserInputHandler;
 use crate::view::query_replace_input::QueryReplaceConfirmInputHandler;
 use crate::view::ui::MenuInputHandler;
@@ -14,134 +17,250 @@ use crossterm::event::KeyEvent;
 use rust_i18n::t;
 
 impl Editor {
-    /// Dispatch input when in terminal mode.
-    ///
-    /// Returns `Some(InputResult)` if terminal mode handled the input,
-    /// `None` if not in terminal mode or if a modal is active.
-    pub fn dispatch_terminal_input(&mut self, event: &KeyEvent) -> Option<InputResult> {
-        // Skip if we're in a prompt/popup (those need to handle keys normally)
-        let in_modal = self.is_prompting()
-            || self.active_state().popups.is_visible()
-            || self.menu_state.active_menu.is_some()
-            || self.settings_state.as_ref().map_or(false, |s| s.visible);
-
-        if in_modal {
-            return None;
+    /// The input layers open now, topmost first
+    pub fn focus_stack(&self) -> FocusStack {
+        let mut layers = Vec::new();
+        if self.notifications.is_focused() {
+            layers.push(InputLayer::Notification);
         }
-
-        // Handle terminal mode input
-        if self.terminal_mode {
-            let mut ctx = InputContext::new();
-            let mut handler =
-                TerminalModeInputHandler::new(self.keyboard_capture, &self.keybindings);
-            let result = handler.dispatch_input(event, &mut ctx);
-            self.process_deferred_actions(ctx);
-            return Some(result);
+        if self.settings_state.as_ref().is_some_and(|s| s.visible) {
+            layers.push(InputLayer::Settings);
         }
-
-        // Check for keys that should re-enter terminal mode from read-only view
-        if self.is_terminal_buffer(self.active_buffer()) && should_enter_terminal_mode(event) {
-            self.enter_terminal_mode();
-            return Some(InputResult::Consumed);
+        if self.menu_state.active_menu.is_some() {
+            layers.push(InputLayer::Menu);
+        }
+        if self.is_prompting() {
+            layers.push(InputLayer::Prompt);
+        }
+        let popups = &self.active_state().popups;
+        if let Some(popup) = popups.top() {
+            layers.push(if popup.transient {
+                InputLayer::Hover
+            } else if popups.is_completion_popup() {
+                InputLayer::Completion
+            } else {
+                InputLayer::Popup
+            });
         }
 
-        None
+        let context = self.key_context;
+        if self.linked_rename.is_some() && context == KeyContext::Normal {
+            layers.push(InputLayer::LinkedRename);
+        }
+        if self.terminal_mode || self.is_terminal_buffer(self.active_buffer()) {
+            layers.push(InputLayer::Terminal);
+        }
+        if matches!(context, KeyContext::Normal | KeyContext::FileExplorer) {
+            if let Some(mode) = &self.editor_mode {
+                layers.push(InputLayer::Mode(mode.clone()));
+            }
+            if let Some(mode) = self.active_buffer_mode() {
+                layers.push(InputLayer::BufferMode(mode.to_string()));
+            }
+        }
+        layers.push(InputLayer::Editor(context));
+        FocusStack::new(layers)
     }
 
-    /// Dispatch input to the appropriate modal handler.
+    /// Offer a key to the surface behind `layer`
     ///
-    /// Returns `Some(InputResult)` if a modal handled the input,
-    /// `None` if no modal is active and input should be handled normally.
-    pub fn dispatch_modal_input(&mut self, event: &KeyEvent) -> Option<InputResult> {
+    /// Keymap layers are resolved together by the keymap pipeline and pass
+    /// everything here.
+    pub(super) fn offer_key_to_layer(
+        &mut self,
+        layer: &InputLayer,
+        event: &KeyEvent,
+    ) -> InputResult {
         let mut ctx = InputContext::new();
-
-        // Settings has highest priority
-        if let Some(ref mut settings) = self.settings_state {
-            if settings.visible {
-                let result = settings.dispatch_input(event, &mut ctx);
+        match layer {
+            InputLayer::Notification => {
+                self.handle_notification_key(event.code, event.modifiers);
+                InputResult::Consumed
+            }
+            InputLayer::Hover => {
+                // Hover and signature help close on any key and let it through
+                self.hide_popup();
+                tracing::debug!("Dismissed transient popup on key press");
+                InputResult::Ignored
+            }
+            InputLayer::Settings => {
+                if let Some(settings) = self.settings_state.as_mut() {
+                    settings.dispatch_input(event, &mut ctx);
+                }
                 self.process_deferred_actions(ctx);
-                return Some(result);
+                InputResult::Consumed
+            }
+            InputLayer::Menu => {
+                let all_menus: Vec<crate::config::Menu> = self
+                    .menus
+                    .menus
+                    .iter()
+                    .chain(self.menu_state.plugin_menus.iter())
+                    .cloned()
+                    .collect();
+
+                let mut handler = MenuInputHandler::new(&mut self.menu_state, &all_menus);
+                handler.dispatch_input(event, &mut ctx);
+                self.process_deferred_actions(ctx);
+                InputResult::Consumed
             }
-        }
-
-        // Menu is next
-        if self.menu_state.active_menu.is_some() {
-            let all_menus: Vec<crate::config::Menu> = self
-                .menus
-                .menus
-                .iter()
-                .chain(self.menu_state.plugin_menus.iter())
-                .cloned()
-                .collect();
-
-            let mut handler = MenuInputHandler::new(&mut self.menu_state, &all_menus);
-            let result = handler.dispatch_input(event, &mut ctx);
-            self.process_deferred_actions(ctx);
-            return Some(result);
-        }
-
-        // Prompt is next
-        if self.prompt.is_some() {
-            // Check for Alt+key keybindings first (before prompt consumes them as modal)
-            if event
-                .modifiers
-                .contains(crossterm::event::KeyModifiers::ALT)
+            InputLayer::Prompt => {
+                self.offer_key_to_prompt(event);
+                InputResult::Consumed
+            }
+            InputLayer::Completion
+                if self.auto_completion_yields_key(event.code, event.modifiers) =>
             {
-                if let crossterm::event::KeyCode::Char(_) = event.code {
-                    let action = self
-                        .keybindings
-                        .resolve(event, crate::input::keybindings::KeyContext::Prompt);
-                    if !matches!(action, Action::None) {
-                        // Handle the action (ignore errors for modal context)
-                        let _ = self.handle_action(action);
-                        return Some(InputResult::Consumed);
-                    }
+                // A completion popup that opened by itself gives up the keys it doesn't use
+                InputResult::Ignored
+            }
+            InputLayer::Popup | InputLayer::Completion => {
+                self.active_state_mut()
+                    .popups
+                    .dispatch_input(event, &mut ctx);
+                self.process_deferred_actions(ctx);
+                InputResult::Consumed
+            }
+            InputLayer::LinkedRename => {
+                // Rename in buffer takes typing, Enter and Escape while it lasts
+                if self.handle_linked_rename_key(event.code, event.modifiers) {
+                    InputResult::Consumed
+                } else {
+                    InputResult::Ignored
                 }
             }
+            InputLayer::Terminal => self.offer_key_to_terminal(event),
+            InputLayer::Mode(_) | InputLayer::BufferMode(_) | InputLayer::Editor(_) => {
+                InputResult::Ignored
+            }
+        }
+    }
 
-            // File browser prompts use FileBrowserInputHandler
-            if self.is_file_open_active() {
-                if let (Some(ref mut file_state), Some(ref mut prompt)) =
-                    (&mut self.file_open_state, &mut self.prompt)
-                {
-                    let mut handler = FileBrowserInputHandler::new(file_state, prompt);
-                    let result = handler.dispatch_input(event, &mut ctx);
-                    self.process_deferred_actions(ctx);
-                    return Some(result);
+    /// Close `layer` for Escape; returns false if there was nothing to close
+    pub(super) fn close_input_layer(&mut self, layer: &InputLayer) -> bool {
+        match layer {
+            InputLayer::Notification => {
+                self.notifications.unfocus();
+                true
+            }
+            InputLayer::Popup | InputLayer::Completion => {
+                self.hide_popup();
+                true
+            }
+            InputLayer::Prompt => {
+                use crate::view::prompt::PromptType;
+                let query_replace = self
+                    .prompt
+                    .as_ref()
+                    .is_some_and(|p| p.prompt_type == PromptType::QueryReplaceConfirm);
+                self.cancel_prompt();
+                if query_replace {
+                    self.interactive_replace_state = None;
                 }
+                true
             }
+            InputLayer::LinkedRename => self.escape_linked_rename(),
+            _ => false,
+        }
+    }
 
-            // QueryReplaceConfirm prompts use QueryReplaceConfirmInputHandler
-            use crate::view::prompt::PromptType;
-            let is_query_replace_confirm = self
-                .prompt
-                .as_ref()
-                .map_or(false, |p| p.prompt_type == PromptType::QueryReplaceConfirm);
-            if is_query_replace_confirm {
-                let mut handler = QueryReplaceConfirmInputHandler::new();
-                let result = handler.dispatch_input(event, &mut ctx);
-                self.process_deferred_actions(ctx);
-                return Some(result);
+    /// Hand a key to the prompt (or the file browser or query-replace
+    /// prompt it stands for)
+    fn offer_key_to_prompt(&mut self, event: &KeyEvent) {
+        let mut ctx = InputContext::new();
+
+        // Check for Alt+key keybindings first (before prompt consumes them as modal)
+        if event
+            .modifiers
+            .contains(crossterm::event::KeyModifiers::ALT)
+        {
+            if let crossterm::event::KeyCode::Char(_) = event.code {
+                let action = self.keybindings.resolve(event, KeyContext::Prompt);
+                if !matches!(action, Action::None) {
+                    // Handle the action (ignore errors for modal context)
+                    let _ = self.handle_action(action);
+                    return;
+                }
             }
+        }
 
-            if let Some(ref mut prompt) = self.prompt {
-                let result = prompt.dispatch_input(event, &mut ctx);
+        // File browser prompts use FileBrowserInputHandler
+        if self.is_file_open_active() {
+            if let (Some(ref mut file_state), Some(ref mut prompt)) =
+                (&mut self.file_open_state, &mut self.prompt)
+            {
+                let mut handler = FileBrowserInputHandler::new(file_state, prompt);
+                handler.dispatch_input(event, &mut ctx);
                 self.process_deferred_actions(ctx);
-                return Some(result);
+                return;
             }
         }
 
-        // Popup is next
-        if self.active_state().popups.is_visible() {
-            let result = self
-                .active_state_mut()
-                .popups
-                .dispatch_input(event, &mut ctx);
+        // QueryReplaceConfirm prompts use QueryReplaceConfirmInputHandler
+        use crate::view::prompt::PromptType;
+        let is_query_replace_confirm = self
+            .prompt
+            .as_ref()
+            .map_or(false, |p| p.prompt_type == PromptType::QueryReplaceConfirm);
+        if is_query_replace_confirm {
+            let mut handler = QueryReplaceConfirmInputHandler::new();
+            handler.dispatch_input(event, &mut ctx);
             self.process_deferred_actions(ctx);
-            return Some(result);
+            return;
         }
 
-        None
+        if let Some(ref mut prompt) = self.prompt {
+            prompt.dispatch_input(event, &mut ctx);
+            self.process_deferred_actions(ctx);
+        }
+    }
+
+    /// Hand a key to the terminal in terminal mode; in a terminal buffer
+    /// shown read-only, keys that go back to terminal mode do so
+    fn offer_key_to_terminal(&mut self, event: &KeyEvent) -> InputResult {
+        if self.terminal_mode {
+            let mut ctx = InputContext::new();
+            let mut handler =
+                TerminalModeInputHandler::new(self.keyboard_capture, &self.keybindings);
+            handler.dispatch_input(event, &mut ctx);
+            self.process_deferred_actions(ctx);
+            return InputResult::Consumed;
+        }
+
+        // Check for keys that should re-enter terminal mode from read-only view
+        if self.is_terminal_buffer(self.active_buffer()) && should_enter_terminal_mode(event) {
+            self.enter_terminal_mode();
+            return InputResult::Consumed;
+        }
+
+        InputResult::Ignored
+    }
+
+    /// Remember which layer consumed `event`
+    pub(super) fn note_key_layer(&mut self, event: &KeyEvent, layer: InputLayer) {
+        let key = format_keybinding(&event.code, &event.modifiers);
+        tracing::debug!("Key {} taken by input layer {}", key, layer.name());
+        self.last_key_layer = Some((key, layer));
+    }
+
+    /// The input layer that consumed the last key
+    pub fn last_key_layer(&self) -> Option<&InputLayer> {
+        self.last_key_layer.as_ref().map(|(_, layer)| layer)
+    }
+
+    /// Show the input layers and which one consumed the last key
+    pub fn show_input_layers(&mut self) {
+        let layers = self.focus_stack().describe();
+        let message = match &self.last_key_layer {
+            Some((key, layer)) => t!(
+                "input_layers.status_last_key",
+                layers = layers,
+                key = key,
+                layer = layer.name()
+            ),
+            None => t!("input_layers.status", layers = layers),
+        };
+        self.set_status_message(message.to_string());
     }
 
     /// Process deferred actions collected during input handling.
@@ -173,15 +292,12 @@ impl Editor {
             }
             DeferredAction::PasteToSettings => {
                 if let Some(text) = self.clipboard.paste() {
-                    if !text.is_empty() {
-                        if let Some(settings) = &mut self.settings_state {
-                            if let Some(dialog) = settings.entry_dialog_mut() {
-                                dialog.insert_str(&text);
-                            }
-                        }
-                    }
+                    self.paste_to_settings(&text);
                 }
             }
+            DeferredAction::CopyFromSettings(text) => {
+                self.clipboard.copy(text);
+            }
             DeferredAction::OpenConfigFile { layer } => {
                 self.open_config_file(layer)?;
             }
@@ -204,6 +320,9 @@ impl Editor {
             DeferredAction::ConfirmPrompt => {
                 self.handle_action(Action::PromptConfirm)?;
             }
+            DeferredAction::ConfirmPromptDryRun => {
+                self.handle_action(Action::PromptConfirmDryRun)?;
+            }
             DeferredAction::UpdatePromptSuggestions => {
                 self.update_prompt_suggestions();
             }
@@ -0,0 +1,452 @@
+//! Input latency tracing
+//!
+//! The event loop reports when each input event was read, when the editor
+//! finished handling it, and when the next frame finished drawing. Samples are
+//! kept in a small ring buffer and summarized as p50/p95/max, shown in an
+//! opt-in HUD, logged periodically at debug level, and streamed to the
+//! `--event-log` file when event logging is enabled.
+//!
+//! When neither the HUD nor event logging is enabled, nothing is recorded.
+
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use ratatui::layout::Rect;
+use ratatui::style::Style;
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Borders, Clear, Paragraph};
+use ratatui::Frame;
+use rust_i18n::t;
+
+use super::Editor;
+use crate::view::glyphs::glyphs;
+
+/// Number of samples kept for the distribution
+const SAMPLE_CAPACITY: usize = 256;
+
+/// How often the HUD numbers are refreshed
+const HUD_REFRESH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// How often the distribution is written to the tracing log
+const LOG_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Most buffers whose overlays and virtual texts the HUD lists
+const HUD_DECORATION_BUFFERS: usize = 5;
+
+/// Timing of a single input event
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LatencySample {
+    /// Kind of input event ("key", "mouse", "paste", "resize")
+    pub kind: &'static str,
+    /// Time from reading the event to the editor finishing handling it
+    pub handle: Duration,
+    /// Time from reading the event to the next frame finishing drawing
+    pub frame: Duration,
+}
+
+/// Percentiles of a set of durations
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LatencyDistribution {
+    pub p50: Duration,
+    pub p95: Duration,
+    pub max: Duration,
+}
+
+impl LatencyDistribution {
+    /// Summarize durations, or None if there are none
+    fn from_durations(mut durations: Vec<Duration>) -> Option<Self> {
+        if durations.is_empty() {
+            return None;
+        }
+        durations.sort_unstable();
+        let percentile = |p: usize| durations[(durations.len() - 1) * p / 100];
+        Some(Self {
+            p50: percentile(50),
+            p95: percentile(95),
+            max: durations[durations.len() - 1],
+        })
+    }
+}
+
+/// Distribution of the recorded samples
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LatencyStats {
+    /// Number of samples summarized
+    pub samples: usize,
+    /// Read-to-handled latency
+    pub handle: LatencyDistribution,
+    /// Read-to-frame-drawn latency
+    pub frame: LatencyDistribution,
+}
+
+/// Ring buffer of input latency samples
+#[derive(Debug, Default)]
+pub struct InputLatencyTracker {
+    /// Whether the HUD is shown
+    hud_visible: bool,
+    /// Whether samples are streamed to the event log
+    event_log: bool,
+    /// Completed samples, oldest first
+    samples: VecDeque<LatencySample>,
+    /// Handled events waiting for the next frame: (kind, read at, handle duration)
+    awaiting_frame: Vec<(&'static str, Instant, Duration)>,
+    /// Stats shown in the HUD (refreshed once per HUD_REFRESH_INTERVAL)
+    hud_stats: Option<LatencyStats>,
+    last_hud_refresh: Option<Instant>,
+    last_log: Option<Instant>,
+}
+
+impl InputLatencyTracker {
+    /// Whether samples are being recorded
+    pub fn is_enabled(&self) -> bool {
+        self.hud_visible || self.event_log
+    }
+
+    /// Record that an event read at `read_at` was handled at `handled_at`
+    pub fn record_handled(&mut self, kind: &'static str, read_at: Instant, handled_at: Instant) {
+        self.awaiting_frame
+            .push((kind, read_at, handled_at.saturating_duration_since(read_at)));
+    }
+
+    /// Complete the samples waiting for a frame, returning them
+    pub fn record_frame(&mut self, drawn_at: Instant) -> Vec<LatencySample> {
+        let completed: Vec<LatencySample> = self
+            .awaiting_frame
+            .drain(..)
+            .map(|(kind, read_at, handle)| LatencySample {
+                kind,
+                handle,
+                frame: drawn_at.saturating_duration_since(read_at),
+            })
+            .collect();
+        for sample in &completed {
+            if self.samples.len() == SAMPLE_CAPACITY {
+                self.samples.pop_front();
+            }
+            self.samples.push_back(*sample);
+        }
+        completed
+    }
+
+    /// Distribution of the recorded samples, or None if there are none
+    pub fn stats(&self) -> Option<LatencyStats> {
+        let handle =
+            LatencyDistribution::from_durations(self.samples.iter().map(|s| s.handle).collect())?;
+        let frame =
+            LatencyDistribution::from_durations(self.samples.iter().map(|s| s.frame).collect())?;
+        Some(LatencyStats {
+            samples: self.samples.len(),
+            handle,
+            frame,
+        })
+    }
+}
+
+/// Format a duration as milliseconds with one decimal
+fn format_ms(duration: Duration) -> String {
+    format!("{:.1}ms", duration.as_secs_f64() * 1000.0)
+}
+
+/// Format one HUD row: "label  p50 …  p95 …  max …"
+fn format_distribution(label: &str, dist: &LatencyDistribution) -> String {
+    format!(
+        "{:<6} p50 {:>7} p95 {:>7} max {:>7}",
+        label,
+        format_ms(dist.p50),
+        format_ms(dist.p95),
+        format_ms(dist.max)
+    )
+}
+
+impl Editor {
+    /// Whether input latency is being recorded (HUD shown or event log enabled)
+    pub fn input_latency_enabled(&self) -> bool {
+        self.input_latency.is_enabled()
+    }
+
+    /// Record that an input event read at `read_at` finished handling at `handled_at`
+    pub fn record_input_handled(
+        &mut self,
+        kind: &'static str,
+        read_at: Instant,
+        handled_at: Instant,
+    ) {
+        if self.input_latency.is_enabled() {
+            self.input_latency.record_handled(kind, read_at, handled_at);
+        }
+    }
+
+    /// Record that a frame finished drawing, completing pending samples
+    pub fn record_frame_drawn(&mut self, drawn_at: Instant) {
+        if self.input_latency.awaiting_frame.is_empty() {
+            return;
+        }
+        let completed = self.input_latency.record_frame(drawn_at);
+        if self.input_latency.event_log {
+            if let Some(event_log) = self.event_logs.get_mut(&self.active_buffer()) {
+                for sample in &completed {
+                    event_log.log_input_latency(sample.kind, sample.handle, sample.frame);
+                }
+            }
+        }
+    }
+
+    /// Distribution of the recorded input latency samples
+    pub fn input_latency_stats(&self) -> Option<LatencyStats> {
+        self.input_latency.stats()
+    }
+
+    /// Refresh the HUD numbers and write the periodic debug log
+    ///
+    /// Returns true if the HUD changed and needs a redraw.
+    pub fn check_input_latency_timer(&mut self) -> bool {
+        if !self.input_latency.is_enabled() {
+            return false;
+        }
+        let now = self.time_source.now();
+
+        let log_due = self
+            .input_latency
+            .last_log
+            .is_none_or(|t| now.duration_since(t) >= LOG_INTERVAL);
+        if log_due {
+            self.input_latency.last_log = Some(now);
+            if let Some(stats) = self.input_latency.stats() {
+                tracing::debug!(
+                    "Input latency over {} samples: handle p50={:?} p95={:?} max={:?}; frame p50={:?} p95={:?} max={:?}",
+                    stats.samples,
+                    stats.handle.p50,
+                    stats.handle.p95,
+                    stats.handle.max,
+                    stats.frame.p50,
+                    stats.frame.p95,
+                    stats.frame.max
+                );
+            }
+        }
+
+        if !self.input_latency.hud_visible {
+            return false;
+        }
+        let refresh_due = self
+            .input_latency
+            .last_hud_refresh
+            .is_none_or(|t| now.duration_since(t) >= HUD_REFRESH_INTERVAL);
+        if !refresh_due {
+            return false;
+        }
+        self.input_latency.last_hud_refresh = Some(now);
+        let stats = self.input_latency.stats();
+        let changed = stats != self.input_latency.hud_stats;
+        self.input_latency.hud_stats = stats;
+        changed
+    }
+
+    /// Toggle the input latency HUD
+    pub fn toggle_input_latency_hud(&mut self) {
+        let tracker = &mut self.input_latency;
+        tracker.hud_visible = !tracker.hud_visible;
+        tracker.hud_stats = tracker.stats();
+        tracker.last_hud_refresh = None;
+        if tracker.hud_visible {
+            self.set_status_message(t!("latency.hud_shown").to_string());
+        } else {
+            self.set_status_message(t!("latency.hud_hidden").to_string());
+        }
+    }
+
+    /// Stream input latency samples to the event log
+    pub(super) fn enable_input_latency_event_log(&mut self) {
+        self.input_latency.event_log = true;
+    }
+
+    /// Render the input latency HUD in the top-right corner
+    pub(super) fn render_input_latency_hud(&self, frame: &mut Frame) {
+        if !self.input_latency.hud_visible {
+            return;
+        }
+
+        let (title, mut lines) = match &self.input_latency.hud_stats {
+            Some(stats) => (
+                t!("latency.hud_title", count = stats.samples).to_string(),
+                vec![
+                    format_distribution(&t!("latency.handle"), &stats.handle),
+                    format_distribution(&t!("latency.frame"), &stats.frame),
+                ],
+            ),
+            None => (
+                t!("latency.hud_title", count = 0).to_string(),
+                vec![t!("latency.waiting").to_string()],
+            ),
+        };
+        if self.config.editor.word_completion != crate::config::WordCompletionScope::Off {
+            let words = self.word_index_stats();
+            lines.push(
+                t!(
+                    "latency.word_index",
+                    words = words.words,
+                    size = words.memory_bytes.div_ceil(1024)
+                )
+                .to_string(),
+            );
+        }
+        // Buffers with the most decoration memory first
+        let mut decorated: Vec<_> = self
+            .buffers
+            .iter()
+            .filter(|(_, state)| !state.overlays.is_empty() || !state.virtual_texts.is_empty())
+            .map(|(id, state)| {
+                let bytes = state.overlays.memory_bytes() + state.virtual_texts.memory_bytes();
+                (bytes, *id, state.overlays.len(), state.virtual_texts.len())
+            })
+            .collect();
+        decorated.sort_by_key(|(bytes, id, _, _)| (std::cmp::Reverse(*bytes), id.0));
+        for (bytes, id, overlays, texts) in decorated.into_iter().take(HUD_DECORATION_BUFFERS) {
+            lines.push(
+                t!(
+                    "latency.decorations",
+                    name = self.get_buffer_display_name(id),
+                    overlays = overlays,
+                    texts = texts,
+                    size = bytes.div_ceil(1024)
+                )
+                .to_string(),
+            );
+        }
+        let queue = self.async_queue_stats();
+        if queue != Default::default() {
+            lines.push(
+                t!(
+                    "latency.async_queue",
+                    queued = queue.queued,
+                    coalesced = queue.coalesced,
+                    dropped = queue.dropped
+                )
+                .to_string(),
+            );
+        }
+        let caches = self.cache_stats();
+        if !caches.is_empty() {
+            let total: usize = caches.iter().map(|c| c.bytes).sum();
+            lines.push(
+                t!(
+                    "latency.cache_budget",
+                    size = total.div_ceil(1024),
+                    budget = self.cache_budget_bytes() / (1024 * 1024)
+                )
+                .to_string(),
+            );
+        }
+        let now = self.time_source.now();
+        for cache in caches {
+            let rate = match cache.hit_rate {
+                Some(rate) => format!("{:.0}%", rate * 100.0),
+                None => "-".to_string(),
+            };
+            let shrunk = match cache.last_shrink {
+                Some(at) => t!(
+                    "latency.cache_shrunk_ago",
+                    secs = now.saturating_duration_since(at).as_secs()
+                )
+                .to_string(),
+                None => t!("latency.cache_never_shrunk").to_string(),
+            };
+            lines.push(
+                t!(
+                    "latency.cache",
+                    name = cache.name,
+                    count = cache.count,
+                    size = cache.bytes.div_ceil(1024),
+                    rate = rate,
+                    shrunk = shrunk
+                )
+                .to_string(),
+            );
+        }
+
+        let content_width = lines
+            .iter()
+            .chain(std::iter::once(&title))
+            .map(|l| crate::primitives::display_width::str_width(l))
+            .max()
+            .unwrap_or(0) as u16;
+        let size = frame.area();
+        let width = (content_width + 4).min(size.width);
+        let height = (lines.len() as u16 + 2).min(size.height);
+        let area = Rect::new(size.width.saturating_sub(width), 1, width, height).intersection(size);
+
+        let style = Style::default()
+            .fg(self.theme.popup_text_fg)
+            .bg(self.theme.popup_bg);
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_set(glyphs().border_set())
+            .border_style(Style::default().fg(self.theme.popup_border_fg))
+            .title(format!(" {} ", title))
+            .style(style);
+        let lines: Vec<Line> = lines
+            .into_iter()
+            .map(|l| Line::from(format!(" {}", l)))
+            .collect();
+
+        frame.render_widget(Clear, area);
+        frame.render_widget(Paragraph::new(lines).block(block), area);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ms(n: u64) -> Duration {
+        Duration::from_millis(n)
+    }
+
+    #[test]
+    fn test_distribution_percentiles() {
+        let dist = LatencyDistribution::from_durations((1..=100).map(ms).collect()).unwrap();
+        assert_eq!(dist.p50, ms(50));
+        assert_eq!(dist.p95, ms(95));
+        assert_eq!(dist.max, ms(100));
+
+        assert!(LatencyDistribution::from_durations(Vec::new()).is_none());
+    }
+
+    #[test]
+    fn test_tracker_completes_samples_on_frame() {
+        let mut tracker = InputLatencyTracker::default();
+        let start = Instant::now();
+        tracker.record_handled("key", start, start + ms(2));
+        tracker.record_handled("key", start + ms(3), start + ms(4));
+        assert!(tracker.stats().is_none());
+
+        let completed = tracker.record_frame(start + ms(10));
+        assert_eq!(completed.len(), 2);
+        assert_eq!(completed[0].handle, ms(2));
+        assert_eq!(completed[0].frame, ms(10));
+        assert_eq!(completed[1].handle, ms(1));
+        assert_eq!(completed[1].frame, ms(7));
+
+        let stats = tracker.stats().unwrap();
+        assert_eq!(stats.samples, 2);
+        assert_eq!(stats.handle.max, ms(2));
+        assert_eq!(stats.frame.max, ms(10));
+
+        // Nothing waiting: a later frame adds no samples
+        assert!(tracker.record_frame(start + ms(20)).is_empty());
+    }
+
+    #[test]
+    fn test_tracker_ring_buffer_is_bounded() {
+        let mut tracker = InputLatencyTracker::default();
+        let start = Instant::now();
+        for i in 0..(SAMPLE_CAPACITY as u64 + 10) {
+            tracker.record_handled("key", start, start + ms(i));
+            tracker.record_frame(start + ms(i));
+        }
+        let stats = tracker.stats().unwrap();
+        assert_eq!(stats.samples, SAMPLE_CAPACITY);
+        // Oldest samples were evicted
+        assert_eq!(tracker.samples.front().unwrap().handle, ms(10));
+    }
+}
@@ -0,0 +1,345 @@
+//! "Insert File..." and "Insert Command Output..."
+//!
+//! Insert File picks a file in the file browser and inserts its text at every
+//! cursor, decoded as when opening it. Files larger than
+//! `editor.large_file_threshold_bytes` are confirmed first.
+//!
+//! Insert Command Output runs a shell command on the worker pool, in the
+//! directory of the buffer's file, and inserts what it prints at every cursor,
+//! replacing the selections. With "selection as stdin" on (toggled with
+//! Alt+I in the prompt, and on to begin with when there is a selection) the
+//! command runs once per cursor with that cursor's selection as its input,
+//! which filters each selection through it (`sort -u`, `tr a-z A-Z`). A
+//! command that fails inserts nothing and shows its error output in a
+//! notification.
+//!
+//! Either way all cursors are edited in one undo step.
+
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+use super::shell_command::run_shell_command;
+use super::Editor;
+use crate::model::cursor::Cursor;
+use crate::model::event::{BufferId, CursorId, Event};
+use crate::services::async_bridge::AsyncMessage;
+use crate::services::worker_pool::{JobHandle, JobPriority};
+use crate::view::notifications::NotificationSeverity;
+use crate::view::prompt::PromptType;
+use rust_i18n::t;
+
+/// Where one cursor's text goes: over its selection, or at its position
+struct CursorEdit {
+    cursor_id: CursorId,
+    selection: Option<Range<usize>>,
+    position: usize,
+}
+
+/// An "Insert Command Output" command that is still running
+pub(crate) struct PendingCommandOutput {
+    request_id: u64,
+    command: String,
+    buffer_id: BufferId,
+    /// Buffer revision when the command started; output is only inserted
+    /// into the text it was run for
+    revision: u64,
+    /// Cursors when the command started, which must not have moved either
+    cursors: Vec<(CursorId, Cursor)>,
+    job: JobHandle,
+}
+
+impl Editor {
+    /// Open the file browser to pick a file to insert
+    pub fn start_insert_file_prompt(&mut self) {
+        self.start_prompt(t!("file.insert_prompt").to_string(), PromptType::InsertFile);
+        self.init_file_open_state();
+    }
+
+    /// Insert the text of the file at `path` at every cursor
+    ///
+    /// Files larger than the large file threshold are confirmed first,
+    /// unless `confirmed` is set.
+    pub(super) fn insert_file(&mut self, path: PathBuf, confirmed: bool) {
+        let name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| path.display().to_string());
+        let size = match std::fs::metadata(&path) {
+            Ok(metadata) => metadata.len(),
+            Err(e) => {
+                self.set_status_message(
+                    t!("file.insert_failed", error = e.to_string()).to_string(),
+                );
+                return;
+            }
+        };
+        if !confirmed && size > self.config.editor.large_file_threshold_bytes {
+            self.start_prompt(
+                t!(
+                    "file.insert_large_confirm",
+                    name = &name,
+                    size = format_size(size)
+                )
+                .to_string(),
+                PromptType::ConfirmInsertLargeFile { path },
+            );
+            return;
+        }
+
+        let contents = match std::fs::read(&path) {
+            Ok(contents) => contents,
+            Err(e) => {
+                self.set_status_message(
+                    t!("file.insert_failed", error = e.to_string()).to_string(),
+                );
+                return;
+            }
+        };
+        let (contents, _) = self.decode_file_contents(&path, contents);
+        // Invalid bytes are kept as placeholders, as when opening the file
+        let contents = crate::model::encoding::escape_invalid_utf8(&contents).unwrap_or(contents);
+        let text = String::from_utf8(contents)
+            .unwrap_or_else(|e| String::from_utf8_lossy(e.as_bytes()).into_owned());
+        let text = text.strip_prefix('\u{feff}').unwrap_or(&text).to_string();
+
+        let edits = self.cursor_edits();
+        let texts = vec![text; edits.len()];
+        let label = t!("undo.insert_file", name = name).to_string();
+        self.replace_at_cursors(edits, texts, label);
+        self.set_status_message(t!("file.inserted", name = name).to_string());
+    }
+
+    /// Prompt for a command whose output to insert
+    pub fn start_insert_command_output_prompt(&mut self) {
+        let selection_as_stdin = self
+            .active_state()
+            .cursors
+            .iter()
+            .any(|(_, cursor)| cursor.selection_range().is_some());
+        self.start_prompt(
+            insert_command_output_message(selection_as_stdin),
+            PromptType::InsertCommandOutput { selection_as_stdin },
+        );
+    }
+
+    /// Toggle whether the Insert Command Output prompt sends the selections
+    /// to the command
+    pub(super) fn toggle_command_output_stdin(&mut self) {
+        let Some(prompt) = self.prompt.as_mut() else {
+            return;
+        };
+        let PromptType::InsertCommandOutput { selection_as_stdin } = &mut prompt.prompt_type else {
+            return;
+        };
+        *selection_as_stdin = !*selection_as_stdin;
+        prompt.message = insert_command_output_message(*selection_as_stdin);
+    }
+
+    /// Start the command of the Insert Command Output prompt
+    pub(super) fn run_insert_command_output(&mut self, command: &str, selection_as_stdin: bool) {
+        if command.trim().is_empty() {
+            return;
+        }
+        if let Some(pending) = self.pending_command_output.take() {
+            pending.job.cancel();
+        }
+
+        let edits = self.cursor_edits();
+        let inputs = if selection_as_stdin {
+            let state = self.active_state_mut();
+            Some(
+                edits
+                    .iter()
+                    .map(|edit| {
+                        edit.selection
+                            .clone()
+                            .map(|range| state.get_text_range(range.start, range.end))
+                            .unwrap_or_default()
+                    })
+                    .collect::<Vec<_>>(),
+            )
+        } else {
+            None
+        };
+
+        let buffer_id = self.active_buffer();
+        let dir = self
+            .active_state()
+            .buffer
+            .file_path()
+            .and_then(Path::parent)
+            .map(Path::to_path_buf)
+            .unwrap_or_else(|| self.working_dir.clone());
+        self.command_output_runs += 1;
+        let request_id = self.command_output_runs;
+        let job_command = command.to_string();
+        let job =
+            self.worker_pool
+                .submit("command-output", JobPriority::UserBlocking, move |token| {
+                    let result = run_for_inputs(&job_command, &dir, inputs);
+                    if token.is_cancelled() {
+                        return None;
+                    }
+                    Some(AsyncMessage::CommandOutputFinished { request_id, result })
+                });
+
+        let state = self.active_state();
+        self.pending_command_output = Some(PendingCommandOutput {
+            request_id,
+            command: command.to_string(),
+            buffer_id,
+            revision: state.buffer.revision(),
+            cursors: state.cursors.iter().map(|(id, c)| (id, *c)).collect(),
+            job,
+        });
+        self.set_status_message(t!("shell.running", command = command).to_string());
+    }
+
+    /// Insert the output of a finished Insert Command Output command
+    pub(super) fn handle_command_output_finished(
+        &mut self,
+        request_id: u64,
+        result: Result<Vec<String>, String>,
+    ) {
+        if self
+            .pending_command_output
+            .as_ref()
+            .is_none_or(|pending| pending.request_id != request_id)
+        {
+            return;
+        }
+        let Some(pending) = self.pending_command_output.take() else {
+            return;
+        };
+
+        let outputs = match result {
+            Ok(outputs) => outputs,
+            Err(error) => {
+                self.set_status_message(
+                    t!("shell.output_failed", command = &pending.command).to_string(),
+                );
+                self.notify(NotificationSeverity::Error, error);
+                return;
+            }
+        };
+
+        let unchanged = self.active_buffer() == pending.buffer_id
+            && self.active_state().buffer.revision() == pending.revision
+            && self
+                .active_state()
+                .cursors
+                .iter()
+                .map(|(id, c)| (id, *c))
+                .eq(pending.cursors.iter().copied());
+        if !unchanged {
+            self.set_status_message(
+                t!("shell.output_discarded", command = &pending.command).to_string(),
+            );
+            return;
+        }
+
+        let edits = self.cursor_edits();
+        let texts = if outputs.len() == edits.len() {
+            outputs
+        } else {
+            let output = outputs.into_iter().next().unwrap_or_default();
+            vec![output; edits.len()]
+        };
+        let label = t!("undo.insert_command_output", command = &pending.command).to_string();
+        self.replace_at_cursors(edits, texts, label);
+        self.set_status_message(t!("status.shell_command_completed").to_string());
+    }
+
+    /// The cursors of the active buffer, in buffer order
+    fn cursor_edits(&self) -> Vec<CursorEdit> {
+        let mut edits: Vec<CursorEdit> = self
+            .active_state()
+            .cursors
+            .iter()
+            .map(|(cursor_id, cursor)| {
+                let selection = cursor.selection_range();
+                let position = selection
+                    .as_ref()
+                    .map_or(cursor.position, |range| range.start);
+                CursorEdit {
+                    cursor_id,
+                    selection,
+                    position,
+                }
+            })
+            .collect();
+        edits.sort_by_key(|edit| edit.position);
+        edits
+    }
+
+    /// Put `texts[i]` over the selection of `edits[i]`, or at its position,
+    /// as one undo step named `label`
+    fn replace_at_cursors(&mut self, edits: Vec<CursorEdit>, texts: Vec<String>, label: String) {
+        let mut events = Vec::new();
+        // From the end, so earlier edits don't move later positions
+        for (edit, text) in edits.into_iter().zip(texts).rev() {
+            if let Some(range) = edit.selection {
+                let deleted_text = self
+                    .active_state_mut()
+                    .get_text_range(range.start, range.end);
+                events.push(Event::Delete {
+                    range,
+                    deleted_text,
+                    cursor_id: edit.cursor_id,
+                });
+            }
+            if !text.is_empty() {
+                let text = text.replace("\r\n", "\n").replace('\r', "\n");
+                events.push(Event::Insert {
+                    position: edit.position,
+                    text: self.with_buffer_line_endings(text),
+                    cursor_id: edit.cursor_id,
+                });
+            }
+        }
+
+        if events.len() > 1 {
+            if let Some(bulk_edit) = self.apply_events_as_bulk_edit(events, label.clone()) {
+                self.active_event_log_mut().append_labeled(bulk_edit, label);
+            }
+        } else if let Some(event) = events.into_iter().next() {
+            self.active_event_log_mut()
+                .append_labeled(event.clone(), label);
+            self.apply_event_to_active_buffer(&event);
+        }
+    }
+}
+
+/// Run `command` in `dir` once with no input, or once per input
+fn run_for_inputs(
+    command: &str,
+    dir: &Path,
+    inputs: Option<Vec<String>>,
+) -> Result<Vec<String>, String> {
+    match inputs {
+        None => run_shell_command(command, Some(dir), None).map(|output| vec![output]),
+        Some(inputs) => inputs
+            .iter()
+            .map(|input| run_shell_command(command, Some(dir), Some(input)))
+            .collect(),
+    }
+}
+
+/// Message of the Insert Command Output prompt
+fn insert_command_output_message(selection_as_stdin: bool) -> String {
+    if selection_as_stdin {
+        t!("shell.filter_prompt").to_string()
+    } else {
+        t!("shell.insert_output_prompt").to_string()
+    }
+}
+
+/// File size for the confirmation of a large insert, e.g. "12.5 MB"
+fn format_size(bytes: u64) -> String {
+    const MB: f64 = 1024.0 * 1024.0;
+    if bytes as f64 >= MB {
+        format!("{:.1} MB", bytes as f64 / MB)
+    } else {
+        format!("{} KB", bytes.div_ceil(1024))
+    }
+}
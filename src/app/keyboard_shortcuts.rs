@@ -0,0 +1,445 @@
+//! Keyboard shortcuts viewer
+//!
+//! "Show Keyboard Shortcuts" lists the effective keymap of the context it
+//! was opened from: the resolver's merged bindings (see
+//! [`KeybindingResolver::keymap`]) with the editor mode and the buffer mode
+//! of the previously active buffer layered on top, in the order `handle_key`
+//! checks them. Every row names the source that won, and a second section
+//! lists the keys bound by more than one source. `/` filters both sections
+//! by key, action or description as the filter is typed.
+//!
+//! Plugins get the same data from `editor.getKeymap(context?)`.
+//!
+//! [`KeybindingResolver::keymap`]: crate::input::keybindings::KeybindingResolver::keymap
+
+use crate::input::keybindings::{
+    Action, BindingSource, KeyContext, KeybindingResolver, KeymapEntry, ShadowedBinding,
+};
+use crate::model::event::BufferId;
+use crate::primitives::text_property::TextPropertyEntry;
+use crate::view::glyphs::glyphs;
+use crate::view::prompt::PromptType;
+use crossterm::event::{KeyCode, KeyModifiers};
+use rust_i18n::t;
+use serde_json::json;
+use std::collections::HashMap;
+
+use super::help;
+use super::Editor;
+
+/// Buffer mode of the keyboard shortcuts view (bindings live in `ModeRegistry::new`)
+pub(crate) const KEYBOARD_SHORTCUTS_MODE: &str = "keyboard-shortcuts";
+
+/// An open keyboard shortcuts view
+pub(crate) struct KeyboardShortcutsPanel {
+    buffer_id: BufferId,
+    /// Context the keymap was taken from
+    context: KeyContext,
+    /// Keymap as it was when the view was opened
+    entries: Vec<KeymapEntry>,
+    /// Case-insensitive filter on key, action and description
+    filter: String,
+}
+
+/// Heading of the group an entry is listed under
+fn category(entry: &KeymapEntry) -> String {
+    match &entry.source {
+        BindingSource::Mode { mode, .. } => {
+            t!("keyboard_shortcuts.category_mode", mode = mode).to_string()
+        }
+        _ => t!(
+            "keyboard_shortcuts.category_context",
+            context = entry.context.to_when_clause()
+        )
+        .to_string(),
+    }
+}
+
+/// Order of the groups: modes first since they are checked first
+fn category_rank(entry: &KeymapEntry, context: KeyContext) -> u8 {
+    match (&entry.source, entry.context) {
+        (BindingSource::Mode { .. }, _) => 0,
+        (_, KeyContext::Global) => 1,
+        (_, c) if c == context => 2,
+        _ => 3,
+    }
+}
+
+fn source_label(source: &BindingSource) -> String {
+    match source {
+        BindingSource::Keymap(name) => {
+            t!("keyboard_shortcuts.source_keymap", name = name).to_string()
+        }
+        BindingSource::User => t!("keyboard_shortcuts.source_user").to_string(),
+        BindingSource::Mode {
+            mode,
+            plugin: Some(plugin),
+        } => t!(
+            "keyboard_shortcuts.source_plugin_mode",
+            plugin = plugin,
+            mode = mode
+        )
+        .to_string(),
+        BindingSource::Mode { mode, plugin: None } => {
+            t!("keyboard_shortcuts.source_mode", mode = mode).to_string()
+        }
+    }
+}
+
+/// "── heading ──" line starting a section
+fn section_heading(heading: &str) -> String {
+    let rule = glyphs().border_horizontal.repeat(2);
+    format!("{rule} {heading} {rule}\n")
+}
+
+impl KeyboardShortcutsPanel {
+    fn matches(&self, entry: &KeymapEntry) -> bool {
+        if self.filter.is_empty() {
+            return true;
+        }
+        let filter = self.filter.to_lowercase();
+        [&entry.key_display(), &entry.action, &entry.description]
+            .iter()
+            .any(|field| field.to_lowercase().contains(&filter))
+    }
+
+    /// Build the view content
+    fn render(&self) -> Vec<TextPropertyEntry> {
+        let mut entries = vec![
+            TextPropertyEntry::text(format!(
+                "{}\n",
+                t!(
+                    "keyboard_shortcuts.title",
+                    context = self.context.to_when_clause()
+                )
+            ))
+            .with_property("type", json!("header")),
+            TextPropertyEntry::text(format!("{}\n", t!("keyboard_shortcuts.help")))
+                .with_property("type", json!("help")),
+        ];
+
+        let mut shown: Vec<&KeymapEntry> =
+            self.entries.iter().filter(|e| self.matches(e)).collect();
+        if !self.filter.is_empty() {
+            entries.push(
+                TextPropertyEntry::text(format!(
+                    "{}\n",
+                    t!(
+                        "keyboard_shortcuts.filter",
+                        filter = &self.filter,
+                        shown = shown.len(),
+                        total = self.entries.len()
+                    )
+                ))
+                .with_property("type", json!("help")),
+            );
+        }
+        entries.push(TextPropertyEntry::text("\n"));
+
+        if shown.is_empty() {
+            entries.push(TextPropertyEntry::text(format!(
+                "  {}\n",
+                t!("keyboard_shortcuts.empty")
+            )));
+            return entries;
+        }
+
+        // Stable sort keeps the resolver's order (by description) within a group
+        shown.sort_by_key(|e| (category_rank(e, self.context), category(e)));
+        let key_width = shown
+            .iter()
+            .map(|e| e.key_display().chars().count())
+            .max()
+            .unwrap_or(0)
+            .min(24);
+        let action_width = shown
+            .iter()
+            .map(|e| e.action.chars().count())
+            .max()
+            .unwrap_or(0)
+            .min(32);
+
+        let mut current_category = None;
+        for entry in &shown {
+            let heading = category(entry);
+            if current_category.as_ref() != Some(&heading) {
+                if current_category.is_some() {
+                    entries.push(TextPropertyEntry::text("\n"));
+                }
+                entries.push(
+                    TextPropertyEntry::text(section_heading(&heading))
+                        .with_property("type", json!("category")),
+                );
+                current_category = Some(heading);
+            }
+            entries.push(
+                TextPropertyEntry::text(format!(
+                    "  {:<key_width$}  {:<action_width$}  {}  [{}]\n",
+                    entry.key_display(),
+                    entry.action,
+                    entry.description,
+                    source_label(&entry.source),
+                ))
+                .with_property("type", json!("binding"))
+                .with_property("action", json!(entry.action)),
+            );
+        }
+
+        entries.push(TextPropertyEntry::text("\n"));
+        entries.push(
+            TextPropertyEntry::text(section_heading(&t!("keyboard_shortcuts.conflicts")))
+                .with_property("type", json!("category")),
+        );
+        let conflicts: Vec<(&KeymapEntry, &ShadowedBinding)> = shown
+            .iter()
+            .flat_map(|entry| {
+                entry
+                    .shadowed
+                    .iter()
+                    .map(move |shadowed| (*entry, shadowed))
+            })
+            .collect();
+        if conflicts.is_empty() {
+            entries.push(TextPropertyEntry::text(format!(
+                "  {}\n",
+                t!("keyboard_shortcuts.no_conflicts")
+            )));
+        }
+        for (entry, shadowed) in conflicts {
+            entries.push(
+                TextPropertyEntry::text(format!(
+                    "  {:<key_width$}  {}\n",
+                    entry.key_display(),
+                    t!(
+                        "keyboard_shortcuts.wins_over",
+                        winner = &entry.action,
+                        winner_source = source_label(&entry.source),
+                        loser = &shadowed.action,
+                        loser_source = source_label(&shadowed.source)
+                    )
+                ))
+                .with_property("type", json!("conflict"))
+                .with_property("action", json!(entry.action)),
+            );
+        }
+
+        entries
+    }
+}
+
+impl Editor {
+    /// The effective keymap of a context, with the source of every binding
+    ///
+    /// In Normal and FileExplorer contexts the bindings of the active buffer's
+    /// mode and then of the editor mode (vi mode and the like) shadow the
+    /// resolver's, as they do in `handle_key`; a mode's own bindings shadow
+    /// those of its parents.
+    pub fn effective_keymap(&self, context: KeyContext) -> Vec<KeymapEntry> {
+        let mut entries = self.keybindings.keymap(context);
+        if !matches!(context, KeyContext::Normal | KeyContext::FileExplorer) {
+            return entries;
+        }
+
+        // Lowest priority first, so that each layer shadows the ones before it
+        let mut layers: Vec<(&str, bool)> = Vec::new();
+        if let Some(mode) = self.active_buffer_mode() {
+            layers.push((mode, false));
+        }
+        if let Some(mode) = self.editor_mode.as_deref() {
+            // Chords are only resolved for the editor mode
+            layers.push((mode, true));
+        }
+
+        let mut by_keys: HashMap<Vec<(KeyCode, KeyModifiers)>, usize> = entries
+            .iter()
+            .enumerate()
+            .map(|(index, entry)| (entry.keys.clone(), index))
+            .collect();
+        for (mode_name, with_chords) in layers {
+            let mut chain = Vec::new();
+            let mut current = Some(mode_name);
+            while let Some(name) = current {
+                let Some(mode) = self.mode_registry.get(name) else {
+                    break;
+                };
+                if chain
+                    .iter()
+                    .any(|m: &&crate::input::buffer_mode::BufferMode| m.name == name)
+                {
+                    break;
+                }
+                chain.push(mode);
+                current = mode.parent.as_deref();
+            }
+
+            for mode in chain.into_iter().rev() {
+                let source = BindingSource::Mode {
+                    mode: mode.name.clone(),
+                    plugin: mode.plugin.clone(),
+                };
+                let singles = mode
+                    .keybindings
+                    .iter()
+                    .map(|(key, command)| (vec![*key], command));
+                let chords = mode
+                    .chord_keybindings
+                    .iter()
+                    .filter(|_| with_chords)
+                    .map(|(keys, command)| (keys.clone(), command));
+                for (keys, command) in singles.chain(chords) {
+                    let entry = KeymapEntry {
+                        context,
+                        keys: keys.clone(),
+                        action: command.clone(),
+                        description: self.command_description(command),
+                        source: source.clone(),
+                        shadowed: Vec::new(),
+                    };
+                    match by_keys.get(&keys) {
+                        Some(&index) => {
+                            let previous = std::mem::replace(&mut entries[index], entry);
+                            entries[index].shadowed.push(ShadowedBinding {
+                                context: previous.context,
+                                action: previous.action,
+                                source: previous.source,
+                            });
+                            entries[index].shadowed.extend(previous.shadowed);
+                        }
+                        None => {
+                            by_keys.insert(keys, entries.len());
+                            entries.push(entry);
+                        }
+                    }
+                }
+            }
+        }
+
+        entries.sort_by(|a, b| {
+            a.description
+                .cmp(&b.description)
+                .then_with(|| a.key_display().cmp(&b.key_display()))
+        });
+        entries
+    }
+
+    /// Description of a mode binding's command
+    fn command_description(&self, command: &str) -> String {
+        let action = Action::from_str(command, &HashMap::new())
+            .unwrap_or_else(|| Action::PluginAction(command.to_string()));
+        if let Action::PluginAction(_) = action {
+            let registered = self
+                .command_registry
+                .read()
+                .unwrap()
+                .get_all()
+                .into_iter()
+                .find(|c| c.action == action);
+            if let Some(registered) = registered {
+                return registered.get_localized_name();
+            }
+        }
+        KeybindingResolver::format_action(&action)
+    }
+
+    /// Open the keyboard shortcuts view for the current context
+    ///
+    /// If the view is already open it is refreshed, unless it is the active
+    /// buffer: its own mode would then replace the one it was opened from.
+    pub fn open_keyboard_shortcuts(&mut self) {
+        if let Some(panel) = &self.keyboard_shortcuts_panel {
+            let buffer_id = panel.buffer_id;
+            if self.buffers.contains_key(&buffer_id) {
+                if self.active_buffer() != buffer_id {
+                    let context = self.get_key_context();
+                    let entries = self.effective_keymap(context);
+                    if let Some(panel) = self.keyboard_shortcuts_panel.as_mut() {
+                        panel.context = context;
+                        panel.entries = entries;
+                    }
+                    self.render_keyboard_shortcuts();
+                    self.set_active_buffer(buffer_id);
+                }
+                return;
+            }
+        }
+
+        let context = self.get_key_context();
+        let entries = self.effective_keymap(context);
+        let buffer_id = self.create_virtual_buffer(
+            help::KEYBOARD_SHORTCUTS_BUFFER_NAME.to_string(),
+            KEYBOARD_SHORTCUTS_MODE.to_string(),
+            true,
+        );
+        if let Some(state) = self.buffers.get_mut(&buffer_id) {
+            state.editing_disabled = true;
+            state.margins.set_line_numbers(false);
+        }
+        self.keyboard_shortcuts_panel = Some(KeyboardShortcutsPanel {
+            buffer_id,
+            context,
+            entries,
+            filter: String::new(),
+        });
+        self.render_keyboard_shortcuts();
+        self.set_active_buffer(buffer_id);
+    }
+
+    /// Prompt for the filter of the keyboard shortcuts view
+    pub(super) fn start_keyboard_shortcuts_filter(&mut self) {
+        let Some(panel) = &self.keyboard_shortcuts_panel else {
+            return;
+        };
+        if self.active_buffer() != panel.buffer_id {
+            return;
+        }
+        let current = panel.filter.clone();
+        self.start_prompt_with_initial_text(
+            t!("keyboard_shortcuts.filter_prompt").to_string(),
+            PromptType::KeyboardShortcutsFilter,
+            current,
+        );
+    }
+
+    /// Show only the shortcuts matching `filter` (all of them if it is empty)
+    pub(super) fn filter_keyboard_shortcuts(&mut self, filter: &str) {
+        let Some(panel) = self.keyboard_shortcuts_panel.as_mut() else {
+            return;
+        };
+        let filter = filter.trim();
+        if panel.filter == filter {
+            return;
+        }
+        panel.filter = filter.to_string();
+        let buffer_id = panel.buffer_id;
+        self.render_keyboard_shortcuts();
+        if let Some(state) = self.buffers.get_mut(&buffer_id) {
+            state.cursors.primary_mut().position = 0;
+        }
+    }
+
+    /// Re-render the view from the panel state
+    fn render_keyboard_shortcuts(&mut self) {
+        let Some(panel) = &self.keyboard_shortcuts_panel else {
+            return;
+        };
+        let buffer_id = panel.buffer_id;
+        let entries = panel.render();
+        if let Err(e) = self.set_virtual_buffer_content(buffer_id, entries) {
+            tracing::error!("Failed to render keyboard shortcuts: {}", e);
+        }
+    }
+
+    /// Answer a plugin's getKeymap request
+    pub(super) fn handle_get_keymap(&mut self, context: Option<String>, request_id: u64) {
+        let entries = match context {
+            None => Ok(self.effective_keymap(self.get_key_context())),
+            Some(name) => KeyContext::from_when_clause(&name)
+                .map(|context| self.effective_keymap(context))
+                .ok_or_else(|| format!("Unknown keybinding context '{}'", name)),
+        };
+        self.send_plugin_response(crate::services::plugins::api::PluginResponse::Keymap {
+            request_id,
+            entries,
+        });
+    }
+}
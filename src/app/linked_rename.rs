@@ -0,0 +1,612 @@
+//! Rename in buffer
+//!
+//! A textual rename for buffers without a language server. Every whole-word
+//! occurrence of the word at the cursor (within the selection, when there is
+//! one) gets a cursor selecting it, and typing replaces all of them at once.
+//! Enter keeps the new text as a single undo step, Escape puts the original
+//! text back. Any other key keeps the new text and then does what it normally
+//! does.
+//!
+//! Occurrences inside strings and comments, as told by the syntax highlighter,
+//! are left out unless the word at the cursor is in one itself; Alt+C toggles
+//! them while renaming. A word with more than [`MAX_LINKED_OCCURRENCES`]
+//! occurrences opens the replace prompt instead.
+
+use crossterm::event::{KeyCode, KeyModifiers};
+use rust_i18n::t;
+use std::ops::Range;
+
+use crate::model::cursor::Cursor;
+use crate::model::event::{BufferId, CursorId, Event};
+use crate::primitives::highlighter::HighlightCategory;
+use crate::primitives::word_navigation::{find_word_end, find_word_start, is_word_char};
+use crate::services::todo_scanner::knows_comments;
+use crate::view::overlay::{Overlay, OverlayFace, OverlayNamespace};
+use crate::view::prompt::PromptType;
+
+use super::Editor;
+
+/// Most occurrences edited at once; more fall back to the replace prompt
+pub(super) const MAX_LINKED_OCCURRENCES: usize = 500;
+
+/// Namespace of the occurrence highlights
+fn linked_rename_namespace() -> OverlayNamespace {
+    OverlayNamespace::from_string("linked-rename".to_string())
+}
+
+/// A whole-word occurrence in the scope of the rename
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Occurrence {
+    /// Offset in the original text
+    start: usize,
+    /// Whether it is inside a string or comment
+    in_string_or_comment: bool,
+}
+
+/// An active rename in buffer
+pub(super) struct LinkedRename {
+    buffer_id: BufferId,
+    /// The word being renamed
+    original: String,
+    /// Every occurrence in the scope, sorted
+    all: Vec<Occurrence>,
+    /// Offsets (in the original text) of the occurrences being edited, sorted
+    occurrences: Vec<usize>,
+    /// Offset of the occurrence at the cursor
+    home: usize,
+    /// Whether occurrences in strings and comments are edited too
+    include_strings: bool,
+    /// Whether the syntax tells strings and comments apart
+    knows_syntax: bool,
+    /// Text currently at every edited occurrence
+    replacement: String,
+    /// Whether anything was typed yet (the first key replaces the word)
+    typed: bool,
+    /// Primary cursor before the rename, restored when it is aborted
+    cursor: Cursor,
+}
+
+impl LinkedRename {
+    /// Occurrences edited with the strings/comments setting
+    fn edited(&self, include_strings: bool) -> Vec<usize> {
+        self.all
+            .iter()
+            .filter(|occurrence| include_strings || !occurrence.in_string_or_comment)
+            .map(|occurrence| occurrence.start)
+            .collect()
+    }
+
+    /// Current ranges of the edited occurrences
+    fn ranges(&self) -> Vec<Range<usize>> {
+        shifted_starts(&self.occurrences, self.original.len(), &self.replacement)
+            .into_iter()
+            .map(|start| start..start + self.replacement.len())
+            .collect()
+    }
+}
+
+/// Start offsets of the whole-word occurrences of `word` in `text`
+fn word_occurrences(text: &str, word: &str) -> Vec<usize> {
+    let bytes = text.as_bytes();
+    text.match_indices(word)
+        .map(|(start, _)| start)
+        .filter(|&start| {
+            let end = start + word.len();
+            (start == 0 || !is_word_char(bytes[start - 1]))
+                && (end == bytes.len() || !is_word_char(bytes[end]))
+        })
+        .collect()
+}
+
+/// Where occurrences of `original_len` bytes starting at `starts` are once
+/// each holds `text`
+fn shifted_starts(starts: &[usize], original_len: usize, text: &str) -> Vec<usize> {
+    starts
+        .iter()
+        .enumerate()
+        .map(|(i, start)| start - i * original_len + i * text.len())
+        .collect()
+}
+
+/// Events replacing `from` with `to` at every occurrence
+///
+/// `starts` are the original offsets of occurrences of `original_len` bytes,
+/// each currently holding `from`. The events go from the last occurrence
+/// backwards so every offset is still valid when it is applied.
+fn replace_events(
+    starts: &[usize],
+    original_len: usize,
+    from: &str,
+    to: &str,
+    cursor_id: CursorId,
+) -> Vec<Event> {
+    let mut events = Vec::with_capacity(starts.len() * 2);
+    for start in shifted_starts(starts, original_len, from).into_iter().rev() {
+        if !from.is_empty() {
+            events.push(Event::Delete {
+                range: start..start + from.len(),
+                deleted_text: from.to_string(),
+                cursor_id,
+            });
+        }
+        if !to.is_empty() {
+            events.push(Event::Insert {
+                position: start,
+                text: to.to_string(),
+                cursor_id,
+            });
+        }
+    }
+    events
+}
+
+/// Whether `range` lies inside the given sorted ranges
+fn in_ranges(ranges: &[Range<usize>], range: &Range<usize>) -> bool {
+    let covers = |offset: usize| ranges.iter().any(|r| r.start <= offset && offset < r.end);
+    !range.is_empty() && covers(range.start) && covers(range.end - 1)
+}
+
+impl Editor {
+    /// Start renaming the word at the cursor everywhere in the buffer, or in
+    /// the selection when there is one
+    pub(crate) fn start_rename_in_buffer(&mut self) {
+        if self.is_editing_disabled() {
+            self.set_status_message(t!("buffer.editing_disabled").to_string());
+            return;
+        }
+        if self.linked_rename.is_some() {
+            return;
+        }
+
+        let buffer_id = self.active_buffer();
+        let context_bytes = self.config.editor.highlight_context_bytes;
+        let state = self.active_state_mut();
+        let cursor = *state.cursors.primary();
+        let word_start = find_word_start(&state.buffer, cursor.position);
+        let word_end = find_word_end(&state.buffer, word_start);
+        if word_start >= word_end {
+            self.set_status_message(t!("rename_in_buffer.no_word").to_string());
+            return;
+        }
+        let word = state.get_text_range(word_start, word_end);
+
+        // A selection of just the word doesn't narrow the rename
+        let scope = cursor
+            .selection_range()
+            .filter(|range| *range != (word_start..word_end))
+            .unwrap_or(0..state.buffer.len());
+        let scoped = scope.len() < state.buffer.len();
+        let text = state.get_text_range(scope.start, scope.end);
+        let starts: Vec<usize> = word_occurrences(&text, &word)
+            .into_iter()
+            .map(|start| scope.start + start)
+            .collect();
+        if starts.is_empty() {
+            self.set_status_message(t!("rename_in_buffer.no_occurrences", word = word).to_string());
+            return;
+        }
+
+        let state = self.buffers.get_mut(&buffer_id).unwrap();
+        let knows_syntax = knows_comments(&state.highlighter);
+        let strings_and_comments: Vec<Range<usize>> = if knows_syntax {
+            let mut ranges: Vec<Range<usize>> = state
+                .highlighter
+                .highlight_viewport(
+                    &state.buffer,
+                    scope.start,
+                    scope.end,
+                    &self.theme,
+                    context_bytes,
+                )
+                .into_iter()
+                .filter(|span| {
+                    matches!(
+                        span.category,
+                        Some(HighlightCategory::String | HighlightCategory::Comment)
+                    )
+                })
+                .map(|span| span.range)
+                .collect();
+            ranges.sort_by_key(|range| range.start);
+            ranges
+        } else {
+            Vec::new()
+        };
+        let all: Vec<Occurrence> = starts
+            .iter()
+            .map(|&start| Occurrence {
+                start,
+                in_string_or_comment: in_ranges(
+                    &strings_and_comments,
+                    &(start..start + word.len()),
+                ),
+            })
+            .collect();
+
+        let home = starts
+            .iter()
+            .copied()
+            .find(|&start| start == word_start)
+            .unwrap_or(starts[0]);
+        let home_in_string = all
+            .iter()
+            .any(|occurrence| occurrence.start == home && occurrence.in_string_or_comment);
+        let only_strings = all.iter().all(|occurrence| occurrence.in_string_or_comment);
+
+        let mut rename = LinkedRename {
+            buffer_id,
+            original: word.clone(),
+            all,
+            occurrences: Vec::new(),
+            home,
+            include_strings: home_in_string || only_strings,
+            knows_syntax,
+            replacement: word.clone(),
+            typed: false,
+            cursor,
+        };
+        rename.occurrences = rename.edited(rename.include_strings);
+
+        if rename.occurrences.len() > MAX_LINKED_OCCURRENCES {
+            self.start_search_prompt(
+                t!("file.replace_prompt").to_string(),
+                PromptType::ReplaceSearch,
+                scoped,
+            );
+            self.search_whole_word = true;
+            if let Some(prompt) = self.prompt.as_mut() {
+                prompt.set_input(word.clone());
+            }
+            self.update_search_highlights(&word);
+            self.set_status_message(
+                t!(
+                    "rename_in_buffer.too_many",
+                    word = word,
+                    count = rename.occurrences.len(),
+                    max = MAX_LINKED_OCCURRENCES
+                )
+                .to_string(),
+            );
+            return;
+        }
+
+        self.linked_rename = Some(rename);
+        self.refresh_linked_rename();
+    }
+
+    /// Handle a key while renaming in buffer
+    ///
+    /// Returns false when the key ended the rename and should be handled as
+    /// usual.
+    pub(super) fn handle_linked_rename_key(
+        &mut self,
+        code: KeyCode,
+        modifiers: KeyModifiers,
+    ) -> bool {
+        let Some(rename) = &self.linked_rename else {
+            return false;
+        };
+        if rename.buffer_id != self.active_buffer() {
+            self.commit_linked_rename();
+            return false;
+        }
+
+        let plain = modifiers.difference(KeyModifiers::SHIFT).is_empty();
+        match code {
+            KeyCode::Enter if modifiers.is_empty() => self.commit_linked_rename(),
+            KeyCode::Esc if modifiers.is_empty() => self.abort_linked_rename(),
+            KeyCode::Char('c') if modifiers == KeyModifiers::ALT => {
+                self.toggle_linked_rename_strings()
+            }
+            KeyCode::Char(ch) if plain => {
+                let mut text = if rename.typed {
+                    rename.replacement.clone()
+                } else {
+                    String::new()
+                };
+                text.push(ch);
+                self.set_linked_rename_text(text);
+            }
+            KeyCode::Backspace if modifiers.is_empty() => {
+                let mut text = rename.replacement.clone();
+                if rename.typed {
+                    text.pop();
+                } else {
+                    text.clear();
+                }
+                self.set_linked_rename_text(text);
+            }
+            _ => {
+                self.commit_linked_rename();
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Put `text` at every edited occurrence
+    fn set_linked_rename_text(&mut self, text: String) {
+        let Some(rename) = self.linked_rename.as_mut() else {
+            return;
+        };
+        rename.typed = true;
+        let cursor_id = self.active_state().cursors.primary_id();
+        let rename = self.linked_rename.as_ref().unwrap();
+        let events = replace_events(
+            &rename.occurrences,
+            rename.original.len(),
+            &rename.replacement,
+            &text,
+            cursor_id,
+        );
+        self.apply_linked_rename_events(events);
+        if let Some(rename) = self.linked_rename.as_mut() {
+            rename.replacement = text;
+        }
+        self.refresh_linked_rename();
+    }
+
+    /// Include or leave out occurrences in strings and comments
+    fn toggle_linked_rename_strings(&mut self) {
+        let Some(rename) = self.linked_rename.as_ref() else {
+            return;
+        };
+        if !rename.knows_syntax {
+            return;
+        }
+        let include_strings = !rename.include_strings;
+        let occurrences = rename.edited(include_strings);
+        if occurrences.is_empty() {
+            self.set_status_message(
+                t!("rename_in_buffer.no_occurrences", word = rename.original).to_string(),
+            );
+            return;
+        }
+
+        // Put the original word back everywhere, then the new text at the new set
+        let cursor_id = self.active_state().cursors.primary_id();
+        let len = rename.original.len();
+        let mut events = replace_events(
+            &rename.occurrences,
+            len,
+            &rename.replacement,
+            &rename.original,
+            cursor_id,
+        );
+        events.extend(replace_events(
+            &occurrences,
+            len,
+            &rename.original,
+            &rename.replacement,
+            cursor_id,
+        ));
+        self.apply_linked_rename_events(events);
+        if let Some(rename) = self.linked_rename.as_mut() {
+            rename.include_strings = include_strings;
+            rename.occurrences = occurrences;
+        }
+        self.refresh_linked_rename();
+    }
+
+    /// Apply live edits without logging them; the rename is logged as one
+    /// step when it is committed
+    fn apply_linked_rename_events(&mut self, events: Vec<Event>) {
+        if events.is_empty() {
+            return;
+        }
+        self.apply_event_to_active_buffer(&Event::Batch {
+            events,
+            description: "Rename in buffer".to_string(),
+        });
+    }
+
+    /// Select every edited occurrence, highlight them and show the status
+    fn refresh_linked_rename(&mut self) {
+        let Some(rename) = self.linked_rename.as_ref() else {
+            return;
+        };
+        let ranges = rename.ranges();
+        let home = rename.occurrences.iter().position(|&s| s == rename.home);
+        let message = if rename.knows_syntax {
+            let strings = if rename.include_strings {
+                t!("rename_in_buffer.strings_included")
+            } else {
+                t!("rename_in_buffer.strings_excluded")
+            };
+            t!(
+                "rename_in_buffer.status",
+                word = rename.original,
+                count = ranges.len(),
+                strings = strings
+            )
+            .to_string()
+        } else {
+            t!(
+                "rename_in_buffer.status_plain",
+                word = rename.original,
+                count = ranges.len()
+            )
+            .to_string()
+        };
+
+        let color = self.theme.semantic_highlight_bg;
+        let namespace = linked_rename_namespace();
+        let state = self.active_state_mut();
+        state
+            .overlays
+            .clear_namespace(&namespace, &mut state.marker_list);
+        for range in &ranges {
+            let overlay = Overlay::with_namespace(
+                &mut state.marker_list,
+                range.clone(),
+                OverlayFace::Background { color },
+                namespace.clone(),
+            );
+            state.overlays.add(overlay, &state.marker_list);
+        }
+
+        // The cursor at the home occurrence is added last so it becomes primary
+        let home = home.unwrap_or(0);
+        let mut order: Vec<usize> = (0..ranges.len()).filter(|&i| i != home).collect();
+        order.push(home);
+        state.cursors.remove_secondary();
+        let first = &ranges[order[0]];
+        *state.cursors.primary_mut() = Cursor::with_selection(first.start, first.end);
+        for &i in &order[1..] {
+            state
+                .cursors
+                .add(Cursor::with_selection(ranges[i].start, ranges[i].end));
+        }
+        self.sync_editor_state_to_split_view_state();
+        self.set_status_message(message);
+    }
+
+    /// Keep the new text, logged as one undo step
+    pub(super) fn commit_linked_rename(&mut self) {
+        let Some(rename) = self.linked_rename.take() else {
+            return;
+        };
+        self.clear_linked_rename_overlays(rename.buffer_id);
+        if rename.replacement == rename.original {
+            self.status_message = None;
+            return;
+        }
+
+        let is_active = rename.buffer_id == self.active_buffer();
+        let Some(state) = self.buffers.get_mut(&rename.buffer_id) else {
+            return;
+        };
+        let events = replace_events(
+            &rename.occurrences,
+            rename.original.len(),
+            &rename.original,
+            &rename.replacement,
+            state.cursors.primary_id(),
+        );
+        if is_active {
+            let home = rename.occurrences.iter().position(|&s| s == rename.home);
+            let ranges = rename.ranges();
+            let end = ranges[home.unwrap_or(0)].end;
+            state.cursors.remove_secondary();
+            state.cursors.primary_mut().move_to(end, false);
+            self.sync_editor_state_to_split_view_state();
+        }
+        if let Some(event_log) = self.event_logs.get_mut(&rename.buffer_id) {
+            let label = t!(
+                "undo.rename",
+                word = rename.original,
+                new = rename.replacement
+            )
+            .to_string();
+            event_log.append_labeled(
+                Event::Batch {
+                    events,
+                    description: format!("Rename '{}'", rename.original),
+                },
+                label,
+            );
+        }
+        self.set_status_message(
+            t!(
+                "rename_in_buffer.renamed",
+                count = rename.occurrences.len(),
+                word = rename.original,
+                new = rename.replacement
+            )
+            .to_string(),
+        );
+    }
+
+    /// Abort a rename in the active buffer, for Escape closing its input
+    /// layer; returns false if there was none to abort
+    pub(super) fn escape_linked_rename(&mut self) -> bool {
+        let active = self.active_buffer();
+        if !self
+            .linked_rename
+            .as_ref()
+            .is_some_and(|rename| rename.buffer_id == active)
+        {
+            return false;
+        }
+        self.abort_linked_rename();
+        true
+    }
+
+    /// Put the original text and cursor back
+    fn abort_linked_rename(&mut self) {
+        let Some(rename) = self.linked_rename.take() else {
+            return;
+        };
+        self.clear_linked_rename_overlays(rename.buffer_id);
+        let cursor_id = self.active_state().cursors.primary_id();
+        let events = replace_events(
+            &rename.occurrences,
+            rename.original.len(),
+            &rename.replacement,
+            &rename.original,
+            cursor_id,
+        );
+        self.apply_linked_rename_events(events);
+        self.update_modified_from_event_log();
+
+        let state = self.active_state_mut();
+        state.cursors.remove_secondary();
+        *state.cursors.primary_mut() = rename.cursor;
+        self.sync_editor_state_to_split_view_state();
+        self.set_status_message(t!("rename_in_buffer.cancelled").to_string());
+    }
+
+    fn clear_linked_rename_overlays(&mut self, buffer_id: BufferId) {
+        if let Some(state) = self.buffers.get_mut(&buffer_id) {
+            state
+                .overlays
+                .clear_namespace(&linked_rename_namespace(), &mut state.marker_list);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_word_occurrences_whole_words_only() {
+        let text = "foo foobar _foo foo.x (foo)\nfoo";
+        assert_eq!(word_occurrences(text, "foo"), vec![0, 16, 23, 28]);
+    }
+
+    #[test]
+    fn test_replace_events_round_trip() {
+        let mut text = "ab = ab + ab".to_string();
+        let starts = word_occurrences(&text, "ab");
+        let apply = |text: &mut String, events: Vec<Event>| {
+            for event in events {
+                match event {
+                    Event::Delete { range, .. } => text.replace_range(range, ""),
+                    Event::Insert {
+                        position,
+                        text: new,
+                        ..
+                    } => text.insert_str(position, &new),
+                    _ => unreachable!(),
+                }
+            }
+        };
+
+        apply(
+            &mut text,
+            replace_events(&starts, 2, "ab", "x", CursorId(0)),
+        );
+        assert_eq!(text, "x = x + x");
+        apply(
+            &mut text,
+            replace_events(&starts, 2, "x", "long", CursorId(0)),
+        );
+        assert_eq!(text, "long = long + long");
+        apply(
+            &mut text,
+            replace_events(&starts, 2, "long", "ab", CursorId(0)),
+        );
+        assert_eq!(text, "ab = ab + ab");
+    }
+}
@@ -5,6 +5,7 @@
 
 use super::Editor;
 use crate::input::commands::Suggestion;
+use crate::services::lsp::manager::{label_language, label_server};
 use crate::view::prompt::{Prompt, PromptType};
 use rust_i18n::t;
 
@@ -51,7 +52,7 @@ impl Editor {
     /// Re-send didOpen notifications for all buffers of a given language.
     ///
     /// Called after LSP server restart to re-register open files.
-    fn reopen_buffers_for_language(&mut self, language: &str) {
+    pub(super) fn reopen_buffers_for_language(&mut self, language: &str) {
         // Collect buffer info first to avoid borrow conflicts
         let buffers_for_language: Vec<_> = self
             .buffer_metadata
@@ -77,10 +78,7 @@ impl Editor {
                 continue; // Skip buffers that aren't fully loaded
             };
 
-            let Some(uri) = url::Url::from_file_path(&buf_path)
-                .ok()
-                .and_then(|u| u.as_str().parse::<lsp_types::Uri>().ok())
-            else {
+            let Some(uri) = crate::services::lsp::uri::path_to_uri(&buf_path) else {
                 continue;
             };
 
@@ -90,23 +88,44 @@ impl Editor {
                 continue;
             };
 
-            if let Some(lsp) = self.lsp.as_mut() {
-                if let Some(handle) = lsp.get_or_spawn(&lang_id) {
-                    let _ = handle.did_open(uri, content, lang_id);
+            // Every server of the language gets it, if it has several
+            let Some(lsp) = self.lsp.as_mut() else {
+                continue;
+            };
+            let mut opened = Vec::new();
+            for (_, handle) in lsp.get_or_spawn_all(&lang_id) {
+                if handle
+                    .did_open(uri.clone(), content.clone(), lang_id.clone())
+                    .is_ok()
+                {
+                    opened.push(handle.id());
                 }
             }
+            if let Some(metadata) = self.buffer_metadata.get_mut(&buffer_id) {
+                metadata.lsp_opened_with.extend(opened);
+            }
         }
     }
 
     /// Handle the LspStop action.
     ///
     /// Shows a prompt to select which LSP server to stop, with suggestions
-    /// for all currently running servers.
+    /// for all currently running servers: a language stops all its servers,
+    /// and each of a language's several servers can be stopped by its label.
     pub fn handle_lsp_stop(&mut self) {
         let running_servers: Vec<String> = self
             .lsp
             .as_ref()
-            .map(|lsp| lsp.running_servers())
+            .map(|lsp| {
+                let mut names = lsp.running_servers();
+                names.extend(
+                    lsp.running_server_labels()
+                        .into_iter()
+                        .filter(|label| label_server(label).is_some()),
+                );
+                names.sort();
+                names
+            })
             .unwrap_or_default();
 
         if running_servers.is_empty() {
@@ -121,8 +140,18 @@ impl Editor {
                 let description = self
                     .lsp
                     .as_ref()
-                    .and_then(|lsp| lsp.get_config(lang))
-                    .map(|c| format!("Command: {}", c.command));
+                    .and_then(|lsp| lsp.get_config(label_language(lang)))
+                    .map(|config| {
+                        let commands: Vec<&str> = config
+                            .servers()
+                            .iter()
+                            .filter(|server| {
+                                label_server(lang).is_none_or(|name| server.server_name() == name)
+                            })
+                            .map(|server| server.command.as_str())
+                            .collect();
+                        format!("Command: {}", commands.join(", "))
+                    });
 
                 Suggestion {
                     text: lang.clone(),
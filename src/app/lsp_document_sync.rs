@@ -0,0 +1,197 @@
+//! Keeping language servers' copies of documents up to date
+//!
+//! Each server hears of edits the way it asked at initialization: the
+//! edited ranges for incremental sync; the whole text for full sync, sent
+//! once edits pause for [`FULL_SYNC_DELAY`] and before any request about
+//! the document; and nothing at all for servers that don't track documents.
+//! Until a server's answer is in, the whole text goes out right away, since
+//! it holds whichever position encoding the server picks.
+//!
+//! A language with several servers syncs each of them its own way; they are
+//! all asked for UTF-16 positions, so the buffer has a single encoding.
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use rust_i18n::t;
+
+use super::Editor;
+use crate::model::event::BufferId;
+use crate::model::position_encoding::PositionEncoding;
+use crate::services::lsp::document_sync::{
+    whole_text_change, DocumentChanges, DocumentSync, ServerSync,
+};
+use crate::services::lsp::manager::detect_language;
+
+/// How long edits pause before a full-sync server gets the whole text
+pub(crate) const FULL_SYNC_DELAY: Duration = Duration::from_millis(100);
+
+/// Document sync state of the editor
+#[derive(Default)]
+pub(crate) struct LspDocumentSync {
+    /// What each initialized server agreed to, by server label
+    servers: HashMap<String, ServerSync>,
+    /// Buffers whose whole text is due to their full-sync servers, with
+    /// when they were last edited
+    pending_full: HashMap<BufferId, Instant>,
+}
+
+impl Editor {
+    /// Note the position encoding and sync kind a language server agreed to
+    pub(super) fn set_lsp_server_sync(&mut self, language: String, sync: ServerSync) {
+        tracing::info!(
+            "LSP ({}) positions in {}, {} document sync",
+            language,
+            sync.encoding.name(),
+            sync.sync.name()
+        );
+        self.lsp_document_sync.servers.insert(language, sync);
+    }
+
+    /// Forget what a language server agreed to, once it stops or restarts
+    pub(super) fn forget_lsp_server_sync(&mut self, label: &str) {
+        self.lsp_document_sync.servers.remove(label);
+    }
+
+    /// What the server with label `label` agreed to, once it's initialized
+    pub(crate) fn lsp_server_sync(&self, label: &str) -> Option<ServerSync> {
+        self.lsp_document_sync.servers.get(label).copied()
+    }
+
+    /// What each initialized server agreed to, for the LSP status
+    pub(super) fn lsp_server_sync_summary(&self) -> Option<String> {
+        let mut servers: Vec<_> = self.lsp_document_sync.servers.iter().collect();
+        if servers.is_empty() {
+            return None;
+        }
+        servers.sort_by(|(a, _), (b, _)| a.cmp(b));
+        let summary: Vec<String> = servers
+            .into_iter()
+            .map(|(label, sync)| {
+                t!(
+                    "lsp.server_sync",
+                    language = label,
+                    encoding = sync.encoding.name(),
+                    sync = sync.sync.name()
+                )
+                .to_string()
+            })
+            .collect();
+        Some(summary.join(", "))
+    }
+
+    /// Labels of the servers a buffer could have, primary first; none if
+    /// LSP is off for it, so its text isn't copied out for nothing
+    pub(crate) fn buffer_lsp_servers(&self, buffer_id: BufferId) -> Vec<String> {
+        let Some(metadata) = self.buffer_metadata.get(&buffer_id) else {
+            return Vec::new();
+        };
+        if !metadata.lsp_enabled {
+            return Vec::new();
+        }
+        let (Some(path), Some(lsp)) = (metadata.file_path(), self.lsp.as_ref()) else {
+            return Vec::new();
+        };
+        detect_language(path, &self.config.languages)
+            .map(|language| lsp.server_labels(&language))
+            .unwrap_or_default()
+    }
+
+    /// Units of the LSP positions for a buffer: those of its servers, UTF-16
+    /// until one is initialized
+    pub(crate) fn lsp_position_encoding(&self, buffer_id: BufferId) -> PositionEncoding {
+        self.buffer_lsp_servers(buffer_id)
+            .iter()
+            .find_map(|label| self.lsp_server_sync(label))
+            .map(|sync| sync.encoding)
+            .unwrap_or_default()
+    }
+
+    /// Tell a buffer's servers of an edit made to it, each the way it syncs
+    pub(crate) fn sync_lsp_changes(&mut self, buffer_id: BufferId, changes: DocumentChanges) {
+        if matches!(&changes, DocumentChanges::Ranges(changes) if changes.is_empty()) {
+            return;
+        }
+        let mut by_range = Vec::new();
+        let mut by_whole_text = Vec::new();
+        for label in self.buffer_lsp_servers(buffer_id) {
+            match (self.lsp_server_sync(&label).map(|s| s.sync), &changes) {
+                (Some(DocumentSync::None), _) => {}
+                (Some(DocumentSync::Full), _) => {
+                    let now = self.time_source.now();
+                    self.lsp_document_sync.pending_full.insert(buffer_id, now);
+                }
+                (Some(DocumentSync::Incremental), DocumentChanges::Ranges(_)) => {
+                    by_range.push(label)
+                }
+                // Not initialized yet, or the edit has no ranges
+                (Some(DocumentSync::Incremental), DocumentChanges::Whole) | (None, _) => {
+                    by_whole_text.push(label)
+                }
+            }
+        }
+        if let DocumentChanges::Ranges(changes) = changes {
+            self.send_lsp_changes_for_buffer(buffer_id, changes, &by_range);
+        }
+        self.send_lsp_whole_text(buffer_id, &by_whole_text);
+    }
+
+    fn send_lsp_whole_text(&mut self, buffer_id: BufferId, servers: &[String]) {
+        if servers.is_empty() {
+            return;
+        }
+        let Some(text) = self
+            .buffers
+            .get(&buffer_id)
+            .and_then(|state| state.buffer.to_string())
+        else {
+            tracing::debug!("Buffer {:?} text not available for didChange", buffer_id);
+            return;
+        };
+        self.send_lsp_changes_for_buffer(buffer_id, vec![whole_text_change(text)], servers);
+    }
+
+    /// Send a buffer's full-sync servers its whole text
+    fn send_lsp_pending_full(&mut self, buffer_id: BufferId) {
+        self.lsp_document_sync.pending_full.remove(&buffer_id);
+        let servers: Vec<String> = self
+            .buffer_lsp_servers(buffer_id)
+            .into_iter()
+            .filter(|label| {
+                self.lsp_server_sync(label).map(|sync| sync.sync) == Some(DocumentSync::Full)
+            })
+            .collect();
+        self.send_lsp_whole_text(buffer_id, &servers);
+    }
+
+    /// Send full-sync servers the whole text of buffers once their edits
+    /// paused
+    ///
+    /// Called every tick of the event loop.
+    pub fn check_lsp_sync_timer(&mut self) {
+        let now = self.time_source.now();
+        let due: Vec<BufferId> = self
+            .lsp_document_sync
+            .pending_full
+            .iter()
+            .filter(|(_, edited)| now.saturating_duration_since(**edited) >= FULL_SYNC_DELAY)
+            .map(|(buffer_id, _)| *buffer_id)
+            .collect();
+        for buffer_id in due {
+            self.send_lsp_pending_full(buffer_id);
+        }
+    }
+
+    /// Send a buffer's servers the whole text still due to them, so a
+    /// request or save that follows sees the buffer as it is
+    pub(crate) fn flush_lsp_sync(&mut self, buffer_id: BufferId) {
+        if self.lsp_document_sync.pending_full.contains_key(&buffer_id) {
+            self.send_lsp_pending_full(buffer_id);
+        }
+    }
+
+    /// Drop the whole text due to a buffer's servers, sent some other way
+    pub(crate) fn cancel_lsp_sync(&mut self, buffer_id: BufferId) {
+        self.lsp_document_sync.pending_full.remove(&buffer_id);
+    }
+}
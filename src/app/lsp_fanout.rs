@@ -0,0 +1,439 @@
+//! Requests answered by every server of a buffer, merged into one answer
+//!
+//! When a language has several servers, completion, hover, go-to-definition,
+//! find references and code actions go to each of them under ids of their
+//! own, grouped under the id the editor waits on. Once all have answered, or
+//! the group's time is up, the answers that came in are merged in server
+//! order, primary first:
+//! - completion items are joined, an item whose label an earlier server
+//!   already offered left out;
+//! - hover texts are shown one after another, each under its server's name;
+//! - locations are joined without repeats, code actions are joined.
+//!
+//! Answers coming after their group was merged are stale and dropped.
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use lsp_types::{CodeActionOrCommand, CompletionItem, Location};
+
+use super::Editor;
+use crate::services::lsp::manager::label_server;
+
+/// How long a merged answer waits for slow servers, for what shows up while
+/// typing or pointing
+pub(crate) const QUICK_FANOUT_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// How long a merged answer waits for slow servers, for what the user asked
+/// for and waits on
+pub(crate) const FANOUT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Start and end of a range, as LSP lines and characters
+type LspRange = ((u32, u32), (u32, u32));
+
+/// One server's answer to a fanned-out request
+#[derive(Debug, Clone)]
+pub(crate) enum LspAnswer {
+    Completion(Vec<CompletionItem>),
+    Hover {
+        contents: String,
+        is_markdown: bool,
+        range: Option<LspRange>,
+    },
+    Definition(Vec<Location>),
+    References(Vec<Location>),
+    CodeActions(Vec<CodeActionOrCommand>),
+}
+
+/// A request sent to several servers
+struct FanoutGroup {
+    /// Id of each server's request and the server's label, primary first
+    requests: Vec<(u64, String)>,
+    answers: HashMap<u64, LspAnswer>,
+    deadline: Instant,
+}
+
+/// Fanned-out requests waiting for answers
+#[derive(Default)]
+pub(crate) struct LspFanout {
+    groups: HashMap<u64, FanoutGroup>,
+    /// Group id of each server's request
+    group_of: HashMap<u64, u64>,
+}
+
+impl LspFanout {
+    /// Wait for the answers to `requests` until `deadline`, to be merged
+    /// under `group_id`
+    pub(crate) fn start(&mut self, group_id: u64, requests: Vec<(u64, String)>, deadline: Instant) {
+        for (request_id, _) in &requests {
+            self.group_of.insert(*request_id, group_id);
+        }
+        self.groups.insert(
+            group_id,
+            FanoutGroup {
+                requests,
+                answers: HashMap::new(),
+                deadline,
+            },
+        );
+    }
+
+    /// Whether `request_id` is one server's part of a group
+    pub(crate) fn is_part(&self, request_id: u64) -> bool {
+        self.group_of.contains_key(&request_id)
+    }
+
+    /// Note a server's answer; once every server answered, the group's id
+    /// and merged answer
+    pub(crate) fn answer(
+        &mut self,
+        request_id: u64,
+        answer: LspAnswer,
+    ) -> Option<(u64, LspAnswer)> {
+        let group_id = *self.group_of.get(&request_id)?;
+        let group = self.groups.get_mut(&group_id)?;
+        group.answers.insert(request_id, answer);
+        if group.answers.len() < group.requests.len() {
+            return None;
+        }
+        self.finish(group_id)
+    }
+
+    /// Groups whose time is up, with what their servers answered so far
+    pub(crate) fn expired(&mut self, now: Instant) -> Vec<(u64, LspAnswer)> {
+        let due: Vec<u64> = self
+            .groups
+            .iter()
+            .filter(|(_, group)| now >= group.deadline)
+            .map(|(group_id, _)| *group_id)
+            .collect();
+        due.into_iter()
+            .filter_map(|group_id| self.finish(group_id))
+            .collect()
+    }
+
+    /// Stop waiting for a group, returning its servers' request ids and
+    /// labels; empty if `group_id` isn't a group
+    pub(crate) fn cancel(&mut self, group_id: u64) -> Vec<(u64, String)> {
+        let Some(group) = self.groups.remove(&group_id) else {
+            return Vec::new();
+        };
+        for (request_id, _) in &group.requests {
+            self.group_of.remove(request_id);
+        }
+        group.requests
+    }
+
+    fn finish(&mut self, group_id: u64) -> Option<(u64, LspAnswer)> {
+        let mut group = self.groups.remove(&group_id)?;
+        for (request_id, _) in &group.requests {
+            self.group_of.remove(request_id);
+        }
+        let answers = group
+            .requests
+            .iter()
+            .filter_map(|(request_id, label)| {
+                let answer = group.answers.remove(request_id)?;
+                Some((label.as_str(), answer))
+            })
+            .collect();
+        merge_answers(answers).map(|answer| (group_id, answer))
+    }
+}
+
+/// Servers' answers merged into one, in the order given; None if there are
+/// none
+fn merge_answers(answers: Vec<(&str, LspAnswer)>) -> Option<LspAnswer> {
+    let mut answers = answers.into_iter();
+    let (first_label, first) = answers.next()?;
+    Some(match first {
+        LspAnswer::Completion(mut items) => {
+            for (_, answer) in answers {
+                if let LspAnswer::Completion(more) = answer {
+                    for item in more {
+                        if !items.iter().any(|known| known.label == item.label) {
+                            items.push(item);
+                        }
+                    }
+                }
+            }
+            LspAnswer::Completion(items)
+        }
+        LspAnswer::Hover {
+            contents,
+            is_markdown,
+            range,
+        } => {
+            let mut sections = vec![(first_label, contents, is_markdown, range)];
+            for (label, answer) in answers {
+                if let LspAnswer::Hover {
+                    contents,
+                    is_markdown,
+                    range,
+                } = answer
+                {
+                    sections.push((label, contents, is_markdown, range));
+                }
+            }
+            sections.retain(|(_, contents, _, _)| !contents.is_empty());
+            merge_hovers(sections)
+        }
+        LspAnswer::Definition(locations) => {
+            LspAnswer::Definition(join_locations(locations, answers, |answer| match answer {
+                LspAnswer::Definition(locations) => Some(locations),
+                _ => None,
+            }))
+        }
+        LspAnswer::References(locations) => {
+            LspAnswer::References(join_locations(locations, answers, |answer| match answer {
+                LspAnswer::References(locations) => Some(locations),
+                _ => None,
+            }))
+        }
+        LspAnswer::CodeActions(mut actions) => {
+            for (_, answer) in answers {
+                if let LspAnswer::CodeActions(more) = answer {
+                    actions.extend(more);
+                }
+            }
+            LspAnswer::CodeActions(actions)
+        }
+    })
+}
+
+/// Hover texts that aren't empty as one; with more than one, each goes under
+/// its server's name and the range is the first server's
+fn merge_hovers(mut sections: Vec<(&str, String, bool, Option<LspRange>)>) -> LspAnswer {
+    if sections.len() <= 1 {
+        let (contents, is_markdown, range) = sections
+            .pop()
+            .map(|(_, contents, is_markdown, range)| (contents, is_markdown, range))
+            .unwrap_or_default();
+        return LspAnswer::Hover {
+            contents,
+            is_markdown,
+            range,
+        };
+    }
+    let range = sections[0].3;
+    let contents: Vec<String> = sections
+        .into_iter()
+        .map(|(label, contents, _, _)| {
+            let name = label_server(label).unwrap_or(label);
+            format!("**{}**\n\n{}", name, contents)
+        })
+        .collect();
+    LspAnswer::Hover {
+        contents: contents.join("\n\n---\n\n"),
+        is_markdown: true,
+        range,
+    }
+}
+
+fn join_locations<'a>(
+    mut locations: Vec<Location>,
+    answers: impl Iterator<Item = (&'a str, LspAnswer)>,
+    of: impl Fn(LspAnswer) -> Option<Vec<Location>>,
+) -> Vec<Location> {
+    for (_, answer) in answers {
+        for location in of(answer).unwrap_or_default() {
+            if !locations.contains(&location) {
+                locations.push(location);
+            }
+        }
+    }
+    locations
+}
+
+impl Editor {
+    /// Hand an answer to a request, merging it with other servers' answers
+    /// first if it's one server's part of a fanned-out request
+    pub(super) fn handle_lsp_answer(&mut self, request_id: u64, answer: LspAnswer) {
+        if !self.lsp_fanout.is_part(request_id) {
+            self.apply_lsp_answer(request_id, answer);
+            return;
+        }
+        if let Some((group_id, merged)) = self.lsp_fanout.answer(request_id, answer) {
+            self.apply_lsp_answer(group_id, merged);
+        }
+    }
+
+    /// Merge what fanned-out requests whose time is up got, leaving out
+    /// servers too slow to answer; true if any were
+    ///
+    /// Called every tick of the event loop.
+    pub fn check_lsp_fanout_deadlines(&mut self) -> bool {
+        let now = self.time_source.now();
+        let expired = self.lsp_fanout.expired(now);
+        let merged_any = !expired.is_empty();
+        for (group_id, merged) in expired {
+            tracing::debug!("LSP request {} merged without slow servers", group_id);
+            self.apply_lsp_answer(group_id, merged);
+        }
+        merged_any
+    }
+
+    fn apply_lsp_answer(&mut self, request_id: u64, answer: LspAnswer) {
+        match answer {
+            LspAnswer::Completion(items) => {
+                if let Err(e) = self.handle_completion_response(request_id, items) {
+                    tracing::error!("Error handling completion response: {}", e);
+                }
+            }
+            LspAnswer::Hover {
+                contents,
+                is_markdown,
+                range,
+            } => self.handle_hover_response(request_id, contents, is_markdown, range),
+            LspAnswer::Definition(locations) => {
+                if let Err(e) = self.handle_goto_definition_response(request_id, locations) {
+                    tracing::error!("Error handling goto definition response: {}", e);
+                }
+            }
+            LspAnswer::References(locations) => {
+                if let Err(e) = self.handle_references_response(request_id, locations) {
+                    tracing::error!("Error handling references response: {}", e);
+                }
+            }
+            LspAnswer::CodeActions(actions) => {
+                if self.is_save_action_request(request_id) {
+                    self.handle_save_action_code_actions(request_id, actions);
+                } else if self.is_batch_code_action_request(request_id) {
+                    self.handle_batch_code_action_response(request_id, actions);
+                } else {
+                    self.handle_code_actions_response(request_id, actions);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(label: &str, detail: &str) -> CompletionItem {
+        CompletionItem {
+            label: label.to_string(),
+            detail: Some(detail.to_string()),
+            ..Default::default()
+        }
+    }
+
+    fn hover(contents: &str) -> LspAnswer {
+        LspAnswer::Hover {
+            contents: contents.to_string(),
+            is_markdown: false,
+            range: None,
+        }
+    }
+
+    fn location(line: u32) -> Location {
+        let position = lsp_types::Position::new(line, 0);
+        Location {
+            uri: "file:///a.py".parse().unwrap(),
+            range: lsp_types::Range::new(position, position),
+        }
+    }
+
+    fn fanout(now: Instant) -> LspFanout {
+        let mut fanout = LspFanout::default();
+        fanout.start(
+            10,
+            vec![
+                (11, "python/pyright".to_string()),
+                (12, "python/ruff".to_string()),
+            ],
+            now + FANOUT_TIMEOUT,
+        );
+        fanout
+    }
+
+    #[test]
+    fn test_completions_merged_in_server_order() {
+        let now = Instant::now();
+        let mut fanout = fanout(now);
+        // The secondary server answers first
+        assert!(fanout
+            .answer(
+                12,
+                LspAnswer::Completion(vec![item("sort", "ruff"), item("noqa", "ruff")])
+            )
+            .is_none());
+        let (group_id, merged) = fanout
+            .answer(11, LspAnswer::Completion(vec![item("sort", "pyright")]))
+            .unwrap();
+        assert_eq!(group_id, 10);
+        let LspAnswer::Completion(items) = merged else {
+            panic!("not a completion: {merged:?}");
+        };
+        let items: Vec<_> = items
+            .iter()
+            .map(|item| (item.label.as_str(), item.detail.as_deref().unwrap()))
+            .collect();
+        assert_eq!(items, [("sort", "pyright"), ("noqa", "ruff")]);
+        assert!(!fanout.is_part(11) && !fanout.is_part(12));
+    }
+
+    #[test]
+    fn test_hovers_shown_under_server_names() {
+        let mut both = fanout(Instant::now());
+        both.answer(11, hover("def sort()"));
+        let (_, merged) = both.answer(12, hover("E501 line too long")).unwrap();
+        let LspAnswer::Hover {
+            contents,
+            is_markdown,
+            ..
+        } = merged
+        else {
+            panic!("not a hover: {merged:?}");
+        };
+        assert!(is_markdown);
+        assert_eq!(
+            contents,
+            "**pyright**\n\ndef sort()\n\n---\n\n**ruff**\n\nE501 line too long"
+        );
+
+        // A single non-empty hover is shown as it is
+        let mut single = fanout(Instant::now());
+        single.answer(12, hover(""));
+        let (_, merged) = single.answer(11, hover("def sort()")).unwrap();
+        assert!(matches!(merged, LspAnswer::Hover { contents, .. } if contents == "def sort()"));
+    }
+
+    #[test]
+    fn test_locations_joined_without_repeats() {
+        let mut fanout = fanout(Instant::now());
+        fanout.answer(11, LspAnswer::References(vec![location(1), location(2)]));
+        let (_, merged) = fanout
+            .answer(12, LspAnswer::References(vec![location(2), location(3)]))
+            .unwrap();
+        let LspAnswer::References(locations) = merged else {
+            panic!("not references: {merged:?}");
+        };
+        assert_eq!(locations, [location(1), location(2), location(3)]);
+    }
+
+    #[test]
+    fn test_expired_group_merges_answers_in() {
+        let now = Instant::now();
+        let mut fanout = fanout(now);
+        fanout.answer(12, LspAnswer::CodeActions(Vec::new()));
+        assert!(fanout.expired(now).is_empty());
+
+        let expired = fanout.expired(now + FANOUT_TIMEOUT);
+        assert_eq!(expired.len(), 1);
+        assert!(matches!(expired[0], (10, LspAnswer::CodeActions(_))));
+        // The slow server's answer is no longer part of a group
+        assert!(!fanout.is_part(11));
+    }
+
+    #[test]
+    fn test_cancel_returns_server_requests() {
+        let mut fanout = fanout(Instant::now());
+        let requests = fanout.cancel(10);
+        assert_eq!(requests.len(), 2);
+        assert!(!fanout.is_part(11));
+        assert!(fanout.cancel(10).is_empty());
+    }
+}
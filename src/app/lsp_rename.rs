@@ -0,0 +1,587 @@
+//! LSP rename
+//!
+//! Rename asks the language server where the symbol at the cursor starts and
+//! ends (textDocument/prepareRename, or the word under the cursor when the
+//! server can't tell), prompts for the new name with the current one filled
+//! in, and sends textDocument/rename. The returned `WorkspaceEdit` becomes a
+//! list of pending edits that is applied all or nothing, one undo step per
+//! buffer. A rename confined to a single file applies right away; anything
+//! wider opens the pending edits review first, with edits to files outside the
+//! working directory unchecked until the user opts in.
+
+use lsp_types::{
+    DocumentChangeOperation, DocumentChanges, OneOf, PrepareRenameResponse, TextEdit, WorkspaceEdit,
+};
+use rust_i18n::t;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::model::event::BufferId;
+use crate::model::pending_edits::PendingEdit;
+use crate::model::position_encoding::PositionEncoding;
+use crate::services::lsp::uri::uri_to_path;
+use crate::view::prompt::{Prompt, PromptType};
+
+use super::pending_edits::{pending_edit_events, valid_pending_edits, OnConfirm};
+use super::Editor;
+
+/// A rename request waiting for the server's edits
+pub(super) struct PendingRename {
+    request_id: u64,
+    buffer_id: BufferId,
+    /// Event log index of the buffer when the rename was requested
+    event_index: usize,
+    new_name: String,
+}
+
+/// Byte offset of an LSP position (line, character offset in `encoding`)
+/// in `content`
+///
+/// A character past the end of the line means the line end; a line past the
+/// end of the content has no offset.
+fn lsp_position_to_offset(
+    content: &str,
+    position: lsp_types::Position,
+    encoding: PositionEncoding,
+) -> Option<usize> {
+    let mut line_start = 0;
+    for _ in 0..position.line {
+        line_start += content[line_start..].find('\n')? + 1;
+    }
+    let line = content[line_start..].split('\n').next().unwrap_or_default();
+    let line = line.strip_suffix('\r').unwrap_or(line);
+    Some(line_start + encoding.byte_offset(line, position.character as usize))
+}
+
+/// Turn one file's LSP edits into pending edits against its current content
+pub(super) fn file_pending_edits(
+    path: &Path,
+    content: &str,
+    edits: Vec<TextEdit>,
+    encoding: PositionEncoding,
+) -> Result<Vec<PendingEdit>, String> {
+    edits
+        .into_iter()
+        .map(|edit| {
+            let start = lsp_position_to_offset(content, edit.range.start, encoding);
+            let end = lsp_position_to_offset(content, edit.range.end, encoding);
+            match (start, end) {
+                (Some(start), Some(end)) if start <= end => Ok(PendingEdit {
+                    file: path.to_path_buf(),
+                    range: start..end,
+                    old_text: content[start..end].to_string(),
+                    new_text: edit.new_text,
+                    enabled: true,
+                }),
+                _ => Err(format!(
+                    "edit at {}:{} is outside {}",
+                    edit.range.start.line + 1,
+                    edit.range.start.character + 1,
+                    path.display()
+                )),
+            }
+        })
+        .collect()
+}
+
+/// Text edits of a workspace edit grouped by file
+///
+/// Creating, renaming or deleting files is refused rather than skipped, so a
+/// rename is never applied halfway.
+pub(super) fn workspace_edit_files(
+    edit: WorkspaceEdit,
+) -> Result<Vec<(PathBuf, Vec<TextEdit>)>, String> {
+    let mut files: Vec<(PathBuf, Vec<TextEdit>)> = Vec::new();
+    let mut add = |uri: &lsp_types::Uri, edits: Vec<TextEdit>| -> Result<(), String> {
+        let path = uri_to_path(uri.as_str()).map_err(|e| e.to_string())?;
+        match files.iter_mut().find(|(file, _)| *file == path) {
+            Some((_, existing)) => existing.extend(edits),
+            None => files.push((path, edits)),
+        }
+        Ok(())
+    };
+
+    if let Some(changes) = edit.changes {
+        let mut changes: Vec<_> = changes.into_iter().collect();
+        changes.sort_by(|(a, _), (b, _)| a.as_str().cmp(b.as_str()));
+        for (uri, edits) in changes {
+            add(&uri, edits)?;
+        }
+    }
+
+    // rust-analyzer sends document_changes instead of changes
+    if let Some(document_changes) = edit.document_changes {
+        let document_edits = match document_changes {
+            DocumentChanges::Edits(edits) => edits,
+            DocumentChanges::Operations(ops) => ops
+                .into_iter()
+                .map(|op| match op {
+                    DocumentChangeOperation::Edit(edit) => Ok(edit),
+                    DocumentChangeOperation::Op(_) => {
+                        Err("creating, renaming or deleting files is not supported".to_string())
+                    }
+                })
+                .collect::<Result<_, _>>()?,
+        };
+        for document_edit in document_edits {
+            let edits = document_edit
+                .edits
+                .into_iter()
+                .map(|edit| match edit {
+                    OneOf::Left(edit) => edit,
+                    OneOf::Right(annotated) => annotated.text_edit,
+                })
+                .collect();
+            add(&document_edit.text_document.uri, edits)?;
+        }
+    }
+
+    Ok(files)
+}
+
+impl Editor {
+    /// Start renaming the symbol at the cursor
+    ///
+    /// The prompt opens once the language server has said what to rename, or
+    /// right away with the word under the cursor if there is no server to ask.
+    pub(crate) fn start_rename(&mut self) -> io::Result<()> {
+        let buffer_id = self.active_buffer();
+        let encoding = self.lsp_position_encoding(buffer_id);
+        let state = self.active_state();
+        let cursor_pos = state.cursors.primary().position;
+        let (line, character) = state.buffer.position_to_lsp_position(cursor_pos, encoding);
+        let request_id = self.next_lsp_request_id;
+
+        let sent = self
+            .with_lsp_for_buffer(buffer_id, |handle, uri, _language| {
+                handle
+                    .prepare_rename(request_id, uri.clone(), line as u32, character as u32)
+                    .is_ok()
+            })
+            .unwrap_or(false);
+
+        if sent {
+            self.next_lsp_request_id += 1;
+            let event_index = self.buffer_event_index(buffer_id);
+            self.pending_prepare_rename_request = Some((request_id, buffer_id, event_index));
+            self.lsp_status = "LSP: rename...".to_string();
+        } else {
+            self.prompt_rename_word_at_cursor();
+        }
+        Ok(())
+    }
+
+    /// Handle prepareRename response from LSP
+    pub(crate) fn handle_prepare_rename_response(
+        &mut self,
+        request_id: u64,
+        supported: bool,
+        result: Result<Option<PrepareRenameResponse>, String>,
+    ) {
+        let Some((pending_id, buffer_id, event_index)) = self.pending_prepare_rename_request else {
+            return;
+        };
+        if pending_id != request_id {
+            tracing::debug!("Ignoring stale prepareRename response: {}", request_id);
+            return;
+        }
+        self.pending_prepare_rename_request = None;
+        self.lsp_status.clear();
+
+        // The answer is about the text as it was when asked
+        if self.active_buffer() != buffer_id || self.buffer_event_index(buffer_id) != event_index {
+            self.status_message = Some(t!("lsp.rename_cancelled").to_string());
+            return;
+        }
+
+        let range = match result {
+            _ if !supported => None,
+            Ok(Some(PrepareRenameResponse::Range(range))) => Some((range, None)),
+            Ok(Some(PrepareRenameResponse::RangeWithPlaceholder { range, placeholder })) => {
+                Some((range, Some(placeholder)))
+            }
+            Ok(Some(PrepareRenameResponse::DefaultBehavior { .. })) => None,
+            Ok(None) => {
+                self.status_message = Some(t!("lsp.no_symbol_at_cursor").to_string());
+                return;
+            }
+            Err(error) => {
+                self.status_message = Some(t!("lsp.rename_failed", error = &error).to_string());
+                return;
+            }
+        };
+
+        let Some((range, placeholder)) = range else {
+            self.prompt_rename_word_at_cursor();
+            return;
+        };
+        let encoding = self.lsp_position_encoding(buffer_id);
+        let state = self.active_state_mut();
+        let start = state.buffer.lsp_position_to_byte(
+            range.start.line as usize,
+            range.start.character as usize,
+            encoding,
+        );
+        let end = state.buffer.lsp_position_to_byte(
+            range.end.line as usize,
+            range.end.character as usize,
+            encoding,
+        );
+        let placeholder = placeholder.unwrap_or_else(|| state.get_text_range(start, end));
+        self.open_rename_prompt(start, end, placeholder);
+    }
+
+    /// Prompt to rename the word under the cursor
+    fn prompt_rename_word_at_cursor(&mut self) {
+        use crate::primitives::word_navigation::{find_word_end, find_word_start};
+
+        let state = self.active_state();
+        let cursor_pos = state.cursors.primary().position;
+        let word_start = find_word_start(&state.buffer, cursor_pos);
+        let word_end = find_word_end(&state.buffer, cursor_pos);
+        if word_start >= word_end {
+            self.status_message = Some(t!("lsp.no_symbol_at_cursor").to_string());
+            return;
+        }
+
+        let word_text = self.active_state_mut().get_text_range(word_start, word_end);
+        self.open_rename_prompt(word_start, word_end, word_text);
+    }
+
+    /// Highlight the symbol and prompt for its new name, pre-filled with `placeholder`
+    fn open_rename_prompt(&mut self, start: usize, end: usize, placeholder: String) {
+        // Create an overlay to highlight the symbol being renamed
+        let overlay_handle = self.add_overlay(
+            None,
+            start..end,
+            crate::model::event::OverlayFace::Background {
+                color: (50, 100, 200), // Blue background for rename
+            },
+            100,
+            Some(t!("lsp.popup_renaming").to_string()),
+        );
+
+        // Store the rename metadata in the PromptType and pre-fill the input with the current name
+        let mut prompt = Prompt::new(
+            "Rename to: ".to_string(),
+            PromptType::LspRename {
+                original_text: placeholder.clone(),
+                start_pos: start,
+                end_pos: end,
+                overlay_handle,
+            },
+        );
+        prompt.set_input(placeholder);
+        self.prompt = Some(prompt);
+    }
+
+    /// Cancel rename mode - removes overlay if the prompt was for LSP rename
+    pub(crate) fn cancel_rename_overlay(&mut self, handle: &crate::view::overlay::OverlayHandle) {
+        self.remove_overlay(handle.clone());
+    }
+
+    /// Perform the actual LSP rename request
+    pub(crate) fn perform_lsp_rename(
+        &mut self,
+        new_name: String,
+        original_text: String,
+        start_pos: usize,
+        overlay_handle: crate::view::overlay::OverlayHandle,
+    ) {
+        // Remove the overlay first
+        self.cancel_rename_overlay(&overlay_handle);
+
+        // Check if the name actually changed
+        if new_name == original_text {
+            self.status_message = Some(t!("lsp.name_unchanged").to_string());
+            return;
+        }
+
+        // Use the position from when we entered rename mode, NOT the current cursor position
+        // This ensures we send the rename request for the correct symbol even if cursor moved
+        let buffer_id = self.active_buffer();
+        let encoding = self.lsp_position_encoding(buffer_id);
+        let state = self.active_state();
+        let (line, character) = state.buffer.position_to_lsp_position(start_pos, encoding);
+        let request_id = self.next_lsp_request_id;
+
+        // Use helper to ensure didOpen is sent before the request
+        let sent = self
+            .with_lsp_for_buffer(buffer_id, |handle, uri, _language| {
+                let result = handle.rename(
+                    request_id,
+                    uri.clone(),
+                    line as u32,
+                    character as u32,
+                    new_name.clone(),
+                );
+                if result.is_ok() {
+                    tracing::info!(
+                        "Requested rename at {}:{}:{} to '{}'",
+                        uri.as_str(),
+                        line,
+                        character,
+                        new_name
+                    );
+                }
+                result.is_ok()
+            })
+            .unwrap_or(false);
+
+        if sent {
+            self.next_lsp_request_id += 1;
+            self.lsp_status = "LSP: rename...".to_string();
+            self.pending_rename_request = Some(PendingRename {
+                request_id,
+                buffer_id,
+                event_index: self.buffer_event_index(buffer_id),
+                new_name,
+            });
+        } else if self
+            .buffer_metadata
+            .get(&buffer_id)
+            .and_then(|m| m.file_path())
+            .is_none()
+        {
+            self.status_message = Some(t!("lsp.cannot_rename_unsaved").to_string());
+        }
+    }
+
+    /// Handle rename response from LSP
+    pub fn handle_rename_response(
+        &mut self,
+        request_id: u64,
+        result: Result<WorkspaceEdit, String>,
+    ) -> io::Result<()> {
+        let pending = match self.pending_rename_request.take() {
+            Some(pending) if pending.request_id != request_id => {
+                tracing::debug!("Ignoring stale rename response: {}", request_id);
+                self.pending_rename_request = Some(pending);
+                return Ok(());
+            }
+            pending => pending,
+        };
+        self.lsp_status.clear();
+
+        let workspace_edit = match result {
+            Ok(workspace_edit) => workspace_edit,
+            Err(error) => {
+                // Per LSP spec: ContentModified errors (-32801) should NOT be shown to user
+                // These are expected when document changes during LSP operations
+                // Reference: https://github.com/neovim/neovim/issues/16900
+                if error.contains("content modified") || error.contains("-32801") {
+                    tracing::debug!(
+                        "LSP rename: ContentModified error (expected, ignoring): {}",
+                        error
+                    );
+                    self.status_message = Some(t!("lsp.rename_cancelled").to_string());
+                } else {
+                    self.status_message = Some(t!("lsp.rename_failed", error = &error).to_string());
+                }
+                return Ok(());
+            }
+        };
+
+        // The edits are positioned against the text as it was when requested
+        if let Some(pending) = &pending {
+            if self.buffer_event_index(pending.buffer_id) != pending.event_index {
+                self.status_message = Some(t!("lsp.rename_cancelled").to_string());
+                return Ok(());
+            }
+        }
+
+        // Positions in every file are in the units of the renaming server
+        let origin = pending
+            .as_ref()
+            .map_or(self.active_buffer(), |pending| pending.buffer_id);
+        let encoding = self.lsp_position_encoding(origin);
+        let mut groups = match self.rename_pending_edits(workspace_edit, encoding) {
+            Ok(groups) => groups,
+            Err(error) => {
+                self.status_message = Some(t!("lsp.rename_failed", error = &error).to_string());
+                return Ok(());
+            }
+        };
+        if groups.is_empty() {
+            self.status_message = Some(t!("lsp.rename_no_edits").to_string());
+            return Ok(());
+        }
+
+        // Files outside the working directory (other than the one being edited)
+        // are listed but left for the user to confirm
+        let origin_path = self
+            .buffer_metadata
+            .get(&origin)
+            .and_then(|metadata| metadata.file_path())
+            .map(|path| self.resolve_pending_edit_path(path));
+        let root = self
+            .working_dir
+            .canonicalize()
+            .unwrap_or_else(|_| self.working_dir.clone());
+        let mut outside = 0;
+        for (path, edits) in &mut groups {
+            if Some(&*path) != origin_path.as_ref() && !path.starts_with(&root) {
+                outside += edits.len();
+                for edit in edits {
+                    edit.enabled = false;
+                }
+            }
+        }
+
+        if groups.len() == 1 && outside == 0 {
+            self.apply_rename_edits(groups);
+            return Ok(());
+        }
+
+        let title = match &pending {
+            Some(pending) => t!("lsp.rename_review_title", name = &pending.new_name).to_string(),
+            None => t!("lsp.popup_renaming").to_string(),
+        };
+        let edits = groups.into_iter().flat_map(|(_, edits)| edits).collect();
+        self.start_pending_edits_review(title, edits, None, OnConfirm::Rename);
+        if outside > 0 {
+            self.status_message =
+                Some(t!("lsp.rename_outside_unchecked", count = outside).to_string());
+        }
+        Ok(())
+    }
+
+    /// Pending edits of a rename, grouped by file, against the current text
+    ///
+    /// Open buffers are read as they are in the editor, other files from disk.
+    fn rename_pending_edits(
+        &self,
+        workspace_edit: WorkspaceEdit,
+        encoding: PositionEncoding,
+    ) -> Result<Vec<(PathBuf, Vec<PendingEdit>)>, String> {
+        let mut groups = Vec::new();
+        for (path, edits) in workspace_edit_files(workspace_edit)? {
+            if edits.is_empty() {
+                continue;
+            }
+            let path = self.resolve_pending_edit_path(&path);
+            let open_buffer = self
+                .buffer_metadata
+                .iter()
+                .find(|(_, metadata)| metadata.file_path() == Some(&path))
+                .map(|(id, _)| *id);
+            let content = match open_buffer {
+                Some(id) => self
+                    .buffers
+                    .get(&id)
+                    .and_then(|state| state.buffer.to_string()),
+                None => std::fs::read_to_string(&path).ok(),
+            }
+            .ok_or_else(|| format!("cannot read {}", path.display()))?;
+            let edits = file_pending_edits(&path, &content, edits, encoding)?;
+            groups.push((path, edits));
+        }
+        Ok(groups)
+    }
+
+    /// Apply the edits of a rename, one bulk edit per buffer
+    ///
+    /// Files that aren't open are opened first. If any edit no longer matches
+    /// the text it was computed against, nothing is applied.
+    pub(super) fn apply_rename_edits(&mut self, groups: Vec<(PathBuf, Vec<PendingEdit>)>) {
+        let mut batches = Vec::new();
+        for (file, edits) in groups {
+            let path = self.resolve_pending_edit_path(&file);
+            let buffer_id = match self.open_file_no_focus(&path) {
+                Ok(id) => id,
+                Err(e) => {
+                    let error = format!("{}: {}", path.display(), e);
+                    self.status_message = Some(t!("lsp.rename_failed", error = &error).to_string());
+                    return;
+                }
+            };
+            let Some(state) = self.buffers.get_mut(&buffer_id) else {
+                return;
+            };
+            let count = edits.len();
+            let valid = valid_pending_edits(state, edits);
+            if valid.len() != count {
+                self.status_message =
+                    Some(t!("lsp.rename_stale", file = path.display().to_string()).to_string());
+                return;
+            }
+            let events = pending_edit_events(&valid, state.cursors.primary_id());
+            batches.push((path, buffer_id, events, count));
+        }
+
+        let mut places = 0;
+        let mut files = 0;
+        for (path, buffer_id, events, count) in batches {
+            let label = t!("undo.rename_places", count = count).to_string();
+            if let Err(e) = self.apply_events_to_buffer_as_bulk_edit(
+                buffer_id,
+                events,
+                "LSP Rename".to_string(),
+                Some(label),
+            ) {
+                tracing::warn!("Failed to apply rename to {:?}: {}", path, e);
+                continue;
+            }
+            places += count;
+            files += 1;
+        }
+        self.status_message = Some(t!("lsp.renamed", count = places, files = files).to_string());
+    }
+
+    /// Current event log index of a buffer (0 if it has no log)
+    fn buffer_event_index(&self, buffer_id: BufferId) -> usize {
+        self.event_logs
+            .get(&buffer_id)
+            .map_or(0, |log| log.current_index())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lsp_types::Position;
+
+    fn position(line: u32, character: u32) -> Position {
+        Position { line, character }
+    }
+
+    #[test]
+    fn test_lsp_position_to_offset() {
+        let content = "fn a() {}\r\nlet é = 1;\n";
+        let offset = |line, character| {
+            lsp_position_to_offset(content, position(line, character), PositionEncoding::Utf16)
+        };
+        assert_eq!(offset(0, 3), Some(3));
+        // CRLF line ending is not part of the line
+        assert_eq!(offset(0, 99), Some(9));
+        // UTF-16 columns past a two-byte character
+        assert_eq!(offset(1, 6), Some(18));
+        // The empty line after the final newline exists, the one after it doesn't
+        assert_eq!(offset(2, 0), Some(content.len()));
+        assert_eq!(offset(3, 0), None);
+
+        // The same place in bytes, for a server that picked UTF-8
+        assert_eq!(
+            lsp_position_to_offset(content, position(1, 7), PositionEncoding::Utf8),
+            Some(18)
+        );
+    }
+
+    #[test]
+    fn test_workspace_edit_refuses_file_operations() {
+        use lsp_types::{CreateFile, ResourceOp, Uri};
+
+        let uri: Uri = "file:///tmp/new.rs".parse().unwrap();
+        let edit = WorkspaceEdit {
+            document_changes: Some(DocumentChanges::Operations(vec![
+                DocumentChangeOperation::Op(ResourceOp::Create(CreateFile {
+                    uri,
+                    options: None,
+                    annotation_id: None,
+                })),
+            ])),
+            ..Default::default()
+        };
+        assert!(workspace_edit_files(edit).is_err());
+    }
+}
@@ -7,19 +7,22 @@
 //! - Find references
 //! - Signature help
 //! - Code actions
-//! - Rename operations
 //! - Inlay hints
 
 use rust_i18n::t;
 use std::io;
+use std::time::Duration;
 
 use lsp_types::TextDocumentContentChangeEvent;
 
 use crate::model::event::{BufferId, Event};
-use crate::services::lsp::manager::detect_language;
-use crate::view::prompt::{Prompt, PromptType};
+use crate::model::position_encoding::PositionEncoding;
+use crate::services::lsp::document_sync::range_changes;
+use crate::services::lsp::manager::{detect_language, label_language};
+use crate::services::lsp::uri::uri_to_path;
 
-use super::{uri_to_path, Editor};
+use super::lsp_fanout::{FANOUT_TIMEOUT, QUICK_FANOUT_TIMEOUT};
+use super::Editor;
 
 impl Editor {
     /// Handle LSP completion response
@@ -40,11 +43,14 @@ impl Editor {
         self.pending_completion_request = None;
         self.lsp_status.clear();
 
-        if items.is_empty() {
-            tracing::debug!("No completion items received");
-            return Ok(());
-        }
+        self.show_completion_popup(items);
+        Ok(())
+    }
 
+    /// Show completion items, with matching words from open buffers added
+    ///
+    /// Does nothing if no item matches the word being typed.
+    pub(crate) fn show_completion_popup(&mut self, items: Vec<lsp_types::CompletionItem>) {
         // Get the partial word at cursor to filter completions
         use crate::primitives::word_navigation::find_completion_word_start;
         let (word_start, cursor_pos) = {
@@ -53,13 +59,19 @@ impl Editor {
             let word_start = find_completion_word_start(&state.buffer, cursor_pos);
             (word_start, cursor_pos)
         };
-        let prefix = if word_start < cursor_pos {
+        let typed = if word_start < cursor_pos {
             self.active_state_mut()
                 .get_text_range(word_start, cursor_pos)
-                .to_lowercase()
         } else {
             String::new()
         };
+        let items = self.add_word_completions(items, &typed);
+        let prefix = typed.to_lowercase();
+
+        if items.is_empty() {
+            tracing::debug!("No completion items received");
+            return;
+        }
 
         // Filter completions to match the typed prefix
         let filtered_items: Vec<&lsp_types::CompletionItem> = if prefix.is_empty() {
@@ -82,7 +94,7 @@ impl Editor {
 
         if filtered_items.is_empty() {
             tracing::debug!("No completion items match prefix '{}'", prefix);
-            return Ok(());
+            return;
         }
 
         // Convert CompletionItem to PopupListItem
@@ -123,6 +135,24 @@ impl Editor {
             })
             .collect();
 
+        // Results for an open completion popup replace it in place, keeping
+        // the selected item if it is still offered
+        let replaces_popup = self.active_state().popups.is_completion_popup();
+        let selected = if replaces_popup {
+            let current = self
+                .active_state()
+                .popups
+                .top()
+                .and_then(|popup| popup.selected_item())
+                .map(|item| item.text.clone());
+            self.hide_popup();
+            current
+                .and_then(|current| popup_items.iter().position(|item| item.text == current))
+                .unwrap_or(0)
+        } else {
+            0
+        };
+
         // Show the popup
         use crate::model::event::{
             PopupContentData, PopupData, PopupListItemData, PopupPositionData,
@@ -141,7 +171,7 @@ impl Editor {
                         data: item.data,
                     })
                     .collect(),
-                selected: 0,
+                selected,
             },
             position: PopupPositionData::BelowCursor,
             width: 50,
@@ -159,8 +189,6 @@ impl Editor {
             "Showing completion popup with {} items",
             self.completion_items.as_ref().map_or(0, |i| i.len())
         );
-
-        Ok(())
     }
 
     /// Handle LSP go-to-definition response
@@ -187,50 +215,64 @@ impl Editor {
 
         // For now, just jump to the first location
         let location = &locations[0];
+        // In the units of the server of the buffer that asked
+        let encoding = self.lsp_position_encoding(self.active_buffer());
 
         // Convert URI to file path
-        if let Ok(path) = uri_to_path(&location.uri) {
-            // Open the file
-            let buffer_id = self.open_file(&path)?;
-
-            // Move cursor to the definition position
-            let line = location.range.start.line as usize;
-            let character = location.range.start.character as usize;
-
-            // Calculate byte position from line and character
-            if let Some(state) = self.buffers.get(&buffer_id) {
-                let position = state.buffer.line_col_to_position(line, character);
-
-                // Move cursor
-                let cursor_id = state.cursors.primary_id();
-                let old_position = state.cursors.primary().position;
-                let old_anchor = state.cursors.primary().anchor;
-                let old_sticky_column = state.cursors.primary().sticky_column;
-                let event = crate::model::event::Event::MoveCursor {
-                    cursor_id,
-                    old_position,
-                    new_position: position,
-                    old_anchor,
-                    new_anchor: None,
-                    old_sticky_column,
-                    new_sticky_column: 0, // Reset sticky column for goto definition
+        match uri_to_path(location.uri.as_str()) {
+            Ok(path) => {
+                // Open the file
+                let Some(buffer_id) = self.open_path(&path)? else {
+                    return Ok(());
                 };
 
-                if let Some(state) = self.buffers.get_mut(&buffer_id) {
-                    state.apply(&event);
+                // Move cursor to the definition position
+                let line = location.range.start.line as usize;
+                let character = location.range.start.character as usize;
+
+                // Calculate byte position from line and character
+                if let Some(state) = self.buffers.get(&buffer_id) {
+                    let position = state.buffer.lsp_position_to_byte(line, character, encoding);
+
+                    // Move cursor
+                    let cursor_id = state.cursors.primary_id();
+                    let old_position = state.cursors.primary().position;
+                    let old_anchor = state.cursors.primary().anchor;
+                    let old_sticky_column = state.cursors.primary().sticky_column;
+                    let event = crate::model::event::Event::MoveCursor {
+                        cursor_id,
+                        old_position,
+                        new_position: position,
+                        old_anchor,
+                        new_anchor: None,
+                        old_sticky_column,
+                        new_sticky_column: 0, // Reset sticky column for goto definition
+                    };
+
+                    if let Some(state) = self.buffers.get_mut(&buffer_id) {
+                        state.apply(&event);
+                    }
                 }
-            }
 
-            self.status_message = Some(
-                t!(
-                    "lsp.jumped_to_definition",
-                    path = path.display().to_string(),
-                    line = line + 1
-                )
-                .to_string(),
-            );
-        } else {
-            self.status_message = Some(t!("lsp.cannot_open_definition").to_string());
+                self.status_message = Some(
+                    t!(
+                        "lsp.jumped_to_definition",
+                        path = path.display().to_string(),
+                        line = line + 1
+                    )
+                    .to_string(),
+                );
+            }
+            Err(e) => {
+                self.status_message = Some(
+                    t!(
+                        "error.cannot_open_uri",
+                        uri = location.uri.as_str(),
+                        error = e.to_string()
+                    )
+                    .to_string(),
+                );
+            }
         }
 
         Ok(())
@@ -262,8 +304,24 @@ impl Editor {
         }
     }
 
-    /// Send a cancel request to the LSP server for a specific request ID
+    /// Send a cancel request to the LSP server for a specific request ID,
+    /// or to each server of a fanned-out request
     fn send_lsp_cancel_request(&mut self, request_id: u64) {
+        let requests = self.lsp_fanout.cancel(request_id);
+        if !requests.is_empty() {
+            let Some(lsp) = self.lsp.as_ref() else {
+                return;
+            };
+            for (request_id, label) in requests {
+                if let Some(handle) = lsp.server(&label) {
+                    if let Err(e) = handle.cancel_request(request_id) {
+                        tracing::warn!("Failed to send LSP cancel request: {}", e);
+                    }
+                }
+            }
+            return;
+        }
+
         // Get the current file path to determine language
         let metadata = self.buffer_metadata.get(&self.active_buffer());
         let file_path = metadata.and_then(|meta| meta.file_path());
@@ -291,82 +349,229 @@ impl Editor {
     /// 3. Ensuring didOpen was sent to this server instance (lazy - only gets text if needed)
     /// 4. Calling the provided closure with the handle
     ///
+    /// The handle is the primary server's when the language has several.
     /// Returns None if any step fails (no file, no language, LSP disabled, etc.)
     pub(crate) fn with_lsp_for_buffer<F, R>(&mut self, buffer_id: BufferId, f: F) -> Option<R>
     where
         F: FnOnce(&crate::services::lsp::async_handler::LspHandle, &lsp_types::Uri, &str) -> R,
     {
+        let (uri, language, servers) =
+            self.open_buffer_with_lsp_servers(buffer_id, |index, _| index == 0)?;
+        let handle = self.lsp.as_ref()?.server(&servers[0])?;
+        Some(f(handle, &uri, &language))
+    }
+
+    /// Execute a closure with the LSP handle of the server with label
+    /// `label`, ensuring didOpen was sent to it first
+    pub(crate) fn with_lsp_server_for_buffer<F, R>(
+        &mut self,
+        buffer_id: BufferId,
+        label: &str,
+        f: F,
+    ) -> Option<R>
+    where
+        F: FnOnce(&crate::services::lsp::async_handler::LspHandle, &lsp_types::Uri, &str) -> R,
+    {
+        let (uri, language, _) =
+            self.open_buffer_with_lsp_servers(buffer_id, |_, server| server == label)?;
+        let handle = self.lsp.as_ref()?.server(label)?;
+        Some(f(handle, &uri, &language))
+    }
+
+    /// Label of the server of `language` to ask for code actions of `kind`:
+    /// the first, primary first, that advertises the kind or a kind it falls
+    /// under, else the primary
+    pub(crate) fn code_action_server(&self, language: &str, kind: &str) -> Option<String> {
+        let servers = self.lsp.as_ref()?.server_labels(language);
+        let advertises = |label: &String| {
+            self.lsp_code_action_kinds.get(label).is_some_and(|kinds| {
+                kinds.iter().any(|advertised| {
+                    advertised == kind || advertised.starts_with(&format!("{}.", kind))
+                })
+            })
+        };
+        servers
+            .iter()
+            .find(|label| advertises(label))
+            .or(servers.first())
+            .cloned()
+    }
+
+    /// Execute a closure with each of a buffer's LSP handles, primary first,
+    /// ensuring didOpen was sent to each; returns the server labels with what
+    /// the closure returned for them
+    pub(crate) fn with_lsp_servers_for_buffer<F, R>(
+        &mut self,
+        buffer_id: BufferId,
+        mut f: F,
+    ) -> Vec<(String, R)>
+    where
+        F: FnMut(&crate::services::lsp::async_handler::LspHandle, &lsp_types::Uri, &str) -> R,
+    {
+        let Some((uri, language, servers)) =
+            self.open_buffer_with_lsp_servers(buffer_id, |_, _| true)
+        else {
+            return Vec::new();
+        };
+        let Some(lsp) = self.lsp.as_ref() else {
+            return Vec::new();
+        };
+        servers
+            .into_iter()
+            .filter_map(|label| {
+                let result = f(lsp.server(&label)?, &uri, &language);
+                Some((label, result))
+            })
+            .collect()
+    }
+
+    /// Send a request about a buffer to each of its servers, with
+    /// `send(handle, uri, request_id)` returning whether it went out
+    ///
+    /// Returns the id the answer comes under, or None if no server took the
+    /// request. With several servers, each gets an id of its own and their
+    /// answers are merged, waiting at most `timeout` for slow ones.
+    pub(crate) fn send_lsp_request_to_all<F>(
+        &mut self,
+        buffer_id: BufferId,
+        timeout: Duration,
+        mut send: F,
+    ) -> Option<u64>
+    where
+        F: FnMut(&crate::services::lsp::async_handler::LspHandle, &lsp_types::Uri, u64) -> bool,
+    {
+        let mut next_id = self.next_lsp_request_id;
+        let sent: Vec<(u64, String)> = self
+            .with_lsp_servers_for_buffer(buffer_id, |handle, uri, _language| {
+                let request_id = next_id;
+                next_id += 1;
+                send(handle, uri, request_id).then_some(request_id)
+            })
+            .into_iter()
+            .filter_map(|(label, request_id)| Some((request_id?, label)))
+            .collect();
+        self.next_lsp_request_id = next_id;
+        match sent.len() {
+            0 => None,
+            // A lone server's answer needs no merging
+            1 => Some(sent[0].0),
+            _ => {
+                let group_id = self.next_lsp_request_id;
+                self.next_lsp_request_id += 1;
+                let deadline = self.time_source.now() + timeout;
+                self.lsp_fanout.start(group_id, sent, deadline);
+                Some(group_id)
+            }
+        }
+    }
+
+    /// Make sure the servers of a buffer that `pick(index, label)` picks,
+    /// primary first, have it open; returns its URI, its language and the
+    /// labels of the picked servers that have it
+    fn open_buffer_with_lsp_servers(
+        &mut self,
+        buffer_id: BufferId,
+        pick: impl Fn(usize, &str) -> bool,
+    ) -> Option<(lsp_types::Uri, String, Vec<String>)> {
+        // The servers must have the buffer's latest text before the request
+        self.flush_lsp_sync(buffer_id);
+
         // Get metadata (immutable borrow first to extract what we need)
-        let (uri, _path, language) = {
+        let (uri, language) = {
             let metadata = self.buffer_metadata.get(&buffer_id)?;
             if !metadata.lsp_enabled {
                 return None;
             }
             let uri = metadata.file_uri()?.clone();
-            let path = metadata.file_path()?.to_path_buf();
-            let language = detect_language(&path, &self.config.languages)?;
-            (uri, path, language)
+            let language = detect_language(metadata.file_path()?, &self.config.languages)?;
+            (uri, language)
         };
 
-        // Get handle ID (spawning if needed)
-        let handle_id = {
-            let lsp = self.lsp.as_mut()?;
-            let handle = lsp.get_or_spawn(&language)?;
-            handle.id()
-        };
+        // Get handle IDs (spawning if needed)
+        let servers: Vec<(String, u64)> = self
+            .lsp
+            .as_mut()?
+            .get_or_spawn_all(&language)
+            .into_iter()
+            .enumerate()
+            .filter(|(index, (label, _))| pick(*index, label))
+            .map(|(_, (label, handle))| (label.to_string(), handle.id()))
+            .collect();
 
-        // Check if didOpen is needed
-        let needs_open = {
-            let metadata = self.buffer_metadata.get(&buffer_id)?;
-            !metadata.lsp_opened_with.contains(&handle_id)
+        let opened: Vec<String> = servers
+            .into_iter()
+            .filter(|(label, handle_id)| {
+                self.open_buffer_with_lsp_server(buffer_id, &uri, &language, label, *handle_id)
+            })
+            .map(|(label, _)| label)
+            .collect();
+        (!opened.is_empty()).then_some((uri, language, opened))
+    }
+
+    /// Send didOpen for a buffer to a server unless it already has it; only
+    /// then gets the text, which can be expensive for large buffers. False
+    /// if the server doesn't have the buffer.
+    fn open_buffer_with_lsp_server(
+        &mut self,
+        buffer_id: BufferId,
+        uri: &lsp_types::Uri,
+        language: &str,
+        label: &str,
+        handle_id: u64,
+    ) -> bool {
+        let Some(metadata) = self.buffer_metadata.get(&buffer_id) else {
+            return false;
         };
+        if metadata.lsp_opened_with.contains(&handle_id) {
+            return true;
+        }
 
-        if needs_open {
-            // Only now get the text (can be expensive for large buffers)
-            let text = self.buffers.get(&buffer_id)?.buffer.to_string()?;
-
-            // Send didOpen
-            {
-                let lsp = self.lsp.as_mut()?;
-                let handle = lsp.get_or_spawn(&language)?;
-                if let Err(e) = handle.did_open(uri.clone(), text, language.clone()) {
-                    tracing::warn!("Failed to send didOpen: {}", e);
-                    return None;
-                }
-            }
+        let Some(text) = self
+            .buffers
+            .get(&buffer_id)
+            .and_then(|state| state.buffer.to_string())
+        else {
+            tracing::debug!("Buffer {:?} text not available for didOpen", buffer_id);
+            return false;
+        };
+        let Some(handle) = self.lsp.as_ref().and_then(|lsp| lsp.server(label)) else {
+            return false;
+        };
+        if let Err(e) = handle.did_open(uri.clone(), text, language.to_string()) {
+            tracing::warn!("Failed to send didOpen: {}", e);
+            return false;
+        }
 
-            // Mark as opened with this server instance
-            let metadata = self.buffer_metadata.get_mut(&buffer_id)?;
+        // Mark as opened with this server instance
+        if let Some(metadata) = self.buffer_metadata.get_mut(&buffer_id) {
             metadata.lsp_opened_with.insert(handle_id);
-
-            tracing::debug!(
-                "Sent didOpen for {} to LSP handle {} (language: {})",
-                uri.as_str(),
-                handle_id,
-                language
-            );
         }
 
-        // Call the closure with the handle
-        let lsp = self.lsp.as_mut()?;
-        let handle = lsp.get_or_spawn(&language)?;
-        Some(f(handle, &uri, &language))
+        tracing::debug!(
+            "Sent didOpen for {} to LSP handle {} ({})",
+            uri.as_str(),
+            handle_id,
+            label
+        );
+        true
     }
 
     /// Request LSP completion at current cursor position
     pub(crate) fn request_completion(&mut self) -> io::Result<()> {
         // Get the current buffer and cursor position
+        let encoding = self.lsp_position_encoding(self.active_buffer());
         let state = self.active_state();
         let cursor_pos = state.cursors.primary().position;
 
-        // Convert byte position to LSP position (line, UTF-16 code units)
-        let (line, character) = state.buffer.position_to_lsp_position(cursor_pos);
+        // Convert byte position to LSP position (line, character offset)
+        let (line, character) = state.buffer.position_to_lsp_position(cursor_pos, encoding);
         let buffer_id = self.active_buffer();
-        let request_id = self.next_lsp_request_id;
 
         // Use helper to ensure didOpen is sent before the request
-        let sent = self
-            .with_lsp_for_buffer(buffer_id, |handle, uri, _language| {
+        let sent = self.send_lsp_request_to_all(
+            buffer_id,
+            QUICK_FANOUT_TIMEOUT,
+            |handle, uri, request_id| {
                 let result =
                     handle.completion(request_id, uri.clone(), line as u32, character as u32);
                 if result.is_ok() {
@@ -378,13 +583,15 @@ impl Editor {
                     );
                 }
                 result.is_ok()
-            })
-            .unwrap_or(false);
+            },
+        );
 
-        if sent {
-            self.next_lsp_request_id += 1;
+        if let Some(request_id) = sent {
             self.pending_completion_request = Some(request_id);
             self.lsp_status = "LSP: completion...".to_string();
+        } else {
+            // No language server: offer words from open buffers right away
+            self.show_completion_popup(Vec::new());
         }
 
         Ok(())
@@ -393,17 +600,17 @@ impl Editor {
     /// Request LSP go-to-definition at current cursor position
     pub(crate) fn request_goto_definition(&mut self) -> io::Result<()> {
         // Get the current buffer and cursor position
+        let encoding = self.lsp_position_encoding(self.active_buffer());
         let state = self.active_state();
         let cursor_pos = state.cursors.primary().position;
 
-        // Convert byte position to LSP position (line, UTF-16 code units)
-        let (line, character) = state.buffer.position_to_lsp_position(cursor_pos);
+        // Convert byte position to LSP position (line, character offset)
+        let (line, character) = state.buffer.position_to_lsp_position(cursor_pos, encoding);
         let buffer_id = self.active_buffer();
-        let request_id = self.next_lsp_request_id;
 
         // Use helper to ensure didOpen is sent before the request
-        let sent = self
-            .with_lsp_for_buffer(buffer_id, |handle, uri, _language| {
+        let sent =
+            self.send_lsp_request_to_all(buffer_id, FANOUT_TIMEOUT, |handle, uri, request_id| {
                 let result =
                     handle.goto_definition(request_id, uri.clone(), line as u32, character as u32);
                 if result.is_ok() {
@@ -415,11 +622,9 @@ impl Editor {
                     );
                 }
                 result.is_ok()
-            })
-            .unwrap_or(false);
+            });
 
-        if sent {
-            self.next_lsp_request_id += 1;
+        if let Some(request_id) = sent {
             self.pending_goto_definition_request = Some(request_id);
         }
 
@@ -429,11 +634,12 @@ impl Editor {
     /// Request LSP hover documentation at current cursor position
     pub(crate) fn request_hover(&mut self) -> io::Result<()> {
         // Get the current buffer and cursor position
+        let encoding = self.lsp_position_encoding(self.active_buffer());
         let state = self.active_state();
         let cursor_pos = state.cursors.primary().position;
 
-        // Convert byte position to LSP position (line, UTF-16 code units)
-        let (line, character) = state.buffer.position_to_lsp_position(cursor_pos);
+        // Convert byte position to LSP position (line, character offset)
+        let (line, character) = state.buffer.position_to_lsp_position(cursor_pos, encoding);
 
         // Debug: Log the position conversion details
         if let Some(pos) = state.buffer.offset_to_position(cursor_pos) {
@@ -447,11 +653,12 @@ impl Editor {
         }
 
         let buffer_id = self.active_buffer();
-        let request_id = self.next_lsp_request_id;
 
         // Use helper to ensure didOpen is sent before the request
-        let sent = self
-            .with_lsp_for_buffer(buffer_id, |handle, uri, _language| {
+        let sent = self.send_lsp_request_to_all(
+            buffer_id,
+            QUICK_FANOUT_TIMEOUT,
+            |handle, uri, request_id| {
                 let result = handle.hover(request_id, uri.clone(), line as u32, character as u32);
                 if result.is_ok() {
                     tracing::info!(
@@ -463,11 +670,10 @@ impl Editor {
                     );
                 }
                 result.is_ok()
-            })
-            .unwrap_or(false);
+            },
+        );
 
-        if sent {
-            self.next_lsp_request_id += 1;
+        if let Some(request_id) = sent {
             self.pending_hover_request = Some(request_id);
             self.lsp_status = "LSP: hover...".to_string();
         }
@@ -479,10 +685,11 @@ impl Editor {
     /// Used for mouse-triggered hover
     pub(crate) fn request_hover_at_position(&mut self, byte_pos: usize) -> io::Result<()> {
         // Get the current buffer
+        let encoding = self.lsp_position_encoding(self.active_buffer());
         let state = self.active_state();
 
-        // Convert byte position to LSP position (line, UTF-16 code units)
-        let (line, character) = state.buffer.position_to_lsp_position(byte_pos);
+        // Convert byte position to LSP position (line, character offset)
+        let (line, character) = state.buffer.position_to_lsp_position(byte_pos, encoding);
 
         // Debug: Log the position conversion details
         if let Some(pos) = state.buffer.offset_to_position(byte_pos) {
@@ -496,11 +703,12 @@ impl Editor {
         }
 
         let buffer_id = self.active_buffer();
-        let request_id = self.next_lsp_request_id;
 
         // Use helper to ensure didOpen is sent before the request
-        let sent = self
-            .with_lsp_for_buffer(buffer_id, |handle, uri, _language| {
+        let sent = self.send_lsp_request_to_all(
+            buffer_id,
+            QUICK_FANOUT_TIMEOUT,
+            |handle, uri, request_id| {
                 let result = handle.hover(request_id, uri.clone(), line as u32, character as u32);
                 if result.is_ok() {
                     tracing::trace!(
@@ -512,11 +720,10 @@ impl Editor {
                     );
                 }
                 result.is_ok()
-            })
-            .unwrap_or(false);
+            },
+        );
 
-        if sent {
-            self.next_lsp_request_id += 1;
+        if let Some(request_id) = sent {
             self.pending_hover_request = Some(request_id);
             self.lsp_status = "LSP: hover...".to_string();
         }
@@ -549,13 +756,17 @@ impl Editor {
 
         // Convert LSP range to byte offsets for highlighting
         if let Some(((start_line, start_char), (end_line, end_char))) = range {
+            let encoding = self.lsp_position_encoding(self.active_buffer());
             let state = self.active_state();
-            let start_byte = state
-                .buffer
-                .lsp_position_to_byte(start_line as usize, start_char as usize);
-            let end_byte = state
-                .buffer
-                .lsp_position_to_byte(end_line as usize, end_char as usize);
+            let start_byte = state.buffer.lsp_position_to_byte(
+                start_line as usize,
+                start_char as usize,
+                encoding,
+            );
+            let end_byte =
+                state
+                    .buffer
+                    .lsp_position_to_byte(end_line as usize, end_char as usize, encoding);
             self.hover_symbol_range = Some((start_byte, end_byte));
             tracing::debug!(
                 "Hover symbol range: {}..{} (LSP {}:{}..{}:{})",
@@ -625,6 +836,9 @@ impl Editor {
         popup.border_style = Style::default().fg(self.theme.popup_border_fg);
         popup.background_style = Style::default().bg(self.theme.popup_bg);
 
+        // The hover takes the place of a plugin's anchored popup
+        self.close_anchored_popup();
+
         // Show the popup
         if let Some(state) = self.buffers.get_mut(&self.active_buffer()) {
             state.popups.show(popup);
@@ -636,6 +850,7 @@ impl Editor {
     pub(crate) fn apply_inlay_hints_to_state(
         state: &mut crate::state::EditorState,
         hints: &[lsp_types::InlayHint],
+        encoding: PositionEncoding,
     ) {
         use crate::view::virtual_text::VirtualTextPosition;
         use ratatui::style::{Color, Style};
@@ -655,6 +870,7 @@ impl Editor {
             let byte_offset = state.buffer.lsp_position_to_byte(
                 hint.position.line as usize,
                 hint.position.character as usize,
+                encoding,
             );
 
             // Extract text from hint label
@@ -692,6 +908,7 @@ impl Editor {
     /// Request LSP find references at current cursor position
     pub(crate) fn request_references(&mut self) -> io::Result<()> {
         // Get the current buffer and cursor position
+        let encoding = self.lsp_position_encoding(self.active_buffer());
         let state = self.active_state();
         let cursor_pos = state.cursors.primary().position;
 
@@ -755,14 +972,13 @@ impl Editor {
             }
         };
 
-        // Convert byte position to LSP position (line, UTF-16 code units)
-        let (line, character) = state.buffer.position_to_lsp_position(cursor_pos);
+        // Convert byte position to LSP position (line, character offset)
+        let (line, character) = state.buffer.position_to_lsp_position(cursor_pos, encoding);
         let buffer_id = self.active_buffer();
-        let request_id = self.next_lsp_request_id;
 
         // Use helper to ensure didOpen is sent before the request
-        let sent = self
-            .with_lsp_for_buffer(buffer_id, |handle, uri, _language| {
+        let sent =
+            self.send_lsp_request_to_all(buffer_id, FANOUT_TIMEOUT, |handle, uri, request_id| {
                 let result =
                     handle.references(request_id, uri.clone(), line as u32, character as u32);
                 if result.is_ok() {
@@ -775,11 +991,9 @@ impl Editor {
                     );
                 }
                 result.is_ok()
-            })
-            .unwrap_or(false);
+            });
 
-        if sent {
-            self.next_lsp_request_id += 1;
+        if let Some(request_id) = sent {
             self.pending_references_request = Some(request_id);
             self.pending_references_symbol = symbol;
             self.lsp_status = "LSP: finding references...".to_string();
@@ -791,11 +1005,12 @@ impl Editor {
     /// Request LSP signature help at current cursor position
     pub(crate) fn request_signature_help(&mut self) -> io::Result<()> {
         // Get the current buffer and cursor position
+        let encoding = self.lsp_position_encoding(self.active_buffer());
         let state = self.active_state();
         let cursor_pos = state.cursors.primary().position;
 
-        // Convert byte position to LSP position (line, UTF-16 code units)
-        let (line, character) = state.buffer.position_to_lsp_position(cursor_pos);
+        // Convert byte position to LSP position (line, character offset)
+        let (line, character) = state.buffer.position_to_lsp_position(cursor_pos, encoding);
         let buffer_id = self.active_buffer();
         let request_id = self.next_lsp_request_id;
 
@@ -945,17 +1160,18 @@ impl Editor {
     /// Request LSP code actions at current cursor position
     pub(crate) fn request_code_actions(&mut self) -> io::Result<()> {
         // Get the current buffer and cursor position
+        let encoding = self.lsp_position_encoding(self.active_buffer());
         let state = self.active_state();
         let cursor_pos = state.cursors.primary().position;
 
-        // Convert byte position to LSP position (line, UTF-16 code units)
-        let (line, character) = state.buffer.position_to_lsp_position(cursor_pos);
+        // Convert byte position to LSP position (line, character offset)
+        let (line, character) = state.buffer.position_to_lsp_position(cursor_pos, encoding);
 
         // Get selection range (if any) or use cursor position
         let (start_line, start_char, end_line, end_char) =
             if let Some(range) = state.cursors.primary().selection_range() {
-                let (s_line, s_char) = state.buffer.position_to_lsp_position(range.start);
-                let (e_line, e_char) = state.buffer.position_to_lsp_position(range.end);
+                let (s_line, s_char) = state.buffer.position_to_lsp_position(range.start, encoding);
+                let (e_line, e_char) = state.buffer.position_to_lsp_position(range.end, encoding);
                 (s_line as u32, s_char as u32, e_line as u32, e_char as u32)
             } else {
                 (line as u32, character as u32, line as u32, character as u32)
@@ -965,11 +1181,10 @@ impl Editor {
         // TODO: Implement diagnostic retrieval when needed
         let diagnostics: Vec<lsp_types::Diagnostic> = Vec::new();
         let buffer_id = self.active_buffer();
-        let request_id = self.next_lsp_request_id;
 
         // Use helper to ensure didOpen is sent before the request
-        let sent = self
-            .with_lsp_for_buffer(buffer_id, |handle, uri, _language| {
+        let sent =
+            self.send_lsp_request_to_all(buffer_id, FANOUT_TIMEOUT, |handle, uri, request_id| {
                 let result = handle.code_actions(
                     request_id,
                     uri.clone(),
@@ -977,7 +1192,8 @@ impl Editor {
                     start_char,
                     end_line,
                     end_char,
-                    diagnostics,
+                    diagnostics.clone(),
+                    None,
                 );
                 if result.is_ok() {
                     tracing::info!(
@@ -991,11 +1207,9 @@ impl Editor {
                     );
                 }
                 result.is_ok()
-            })
-            .unwrap_or(false);
+            });
 
-        if sent {
-            self.next_lsp_request_id += 1;
+        if let Some(request_id) = sent {
             self.pending_code_actions_request = Some(request_id);
             self.lsp_status = "LSP: code actions...".to_string();
         }
@@ -1095,11 +1309,9 @@ impl Editor {
             .iter()
             .map(|loc| {
                 // Convert URI to file path
-                let file = if loc.uri.scheme().map(|s| s.as_str()) == Some("file") {
-                    // Extract path from file:// URI
-                    loc.uri.path().as_str().to_string()
-                } else {
-                    loc.uri.as_str().to_string()
+                let file = match uri_to_path(loc.uri.as_str()) {
+                    Ok(path) => path.to_string_lossy().into_owned(),
+                    Err(_) => loc.uri.as_str().to_string(),
                 };
 
                 crate::services::plugins::hooks::LspLocation {
@@ -1134,6 +1346,88 @@ impl Editor {
         Ok(())
     }
 
+    /// Ask the language server to reformat after typing `ch`, if on-type formatting
+    /// is enabled for the buffer's language (LSP onTypeFormatting)
+    pub(crate) fn request_on_type_formatting(&mut self, ch: char) {
+        let buffer_id = self.active_buffer();
+        let enabled = self
+            .buffer_metadata
+            .get(&buffer_id)
+            .and_then(|metadata| metadata.file_path())
+            .and_then(|path| detect_language(path, &self.config.languages))
+            .and_then(|language| self.config.languages.get(&language))
+            .is_some_and(|config| config.on_type_formatting);
+        if !enabled {
+            return;
+        }
+
+        let encoding = self.lsp_position_encoding(self.active_buffer());
+        let state = self.active_state();
+        let cursor_pos = state.cursors.primary().position;
+        let (line, character) = state.buffer.position_to_lsp_position(cursor_pos, encoding);
+        let tab_size = state.tab_size as u32;
+        let insert_spaces = !state.use_tabs;
+        let request_id = self.next_lsp_request_id;
+
+        let sent = self
+            .with_lsp_for_buffer(buffer_id, |handle, uri, _language| {
+                handle
+                    .on_type_formatting(
+                        request_id,
+                        uri.clone(),
+                        line as u32,
+                        character as u32,
+                        ch,
+                        tab_size,
+                        insert_spaces,
+                    )
+                    .is_ok()
+            })
+            .unwrap_or(false);
+
+        if sent {
+            self.next_lsp_request_id += 1;
+            let event_index = self
+                .event_logs
+                .get(&buffer_id)
+                .map_or(0, |log| log.current_index());
+            self.pending_on_type_formatting_request = Some((request_id, buffer_id, event_index));
+        }
+    }
+
+    /// Handle on-type formatting response from LSP
+    pub(crate) fn handle_on_type_formatting_response(
+        &mut self,
+        request_id: u64,
+        edits: Vec<lsp_types::TextEdit>,
+    ) -> io::Result<()> {
+        let Some((pending_id, buffer_id, event_index)) = self.pending_on_type_formatting_request
+        else {
+            return Ok(());
+        };
+        if pending_id != request_id {
+            tracing::debug!("Ignoring stale on-type formatting response: {}", request_id);
+            return Ok(());
+        }
+        self.pending_on_type_formatting_request = None;
+
+        // The edits are positioned against the text as it was when requested
+        let current_index = self
+            .event_logs
+            .get(&buffer_id)
+            .map(|log| log.current_index());
+        if current_index != Some(event_index) {
+            tracing::debug!(
+                "Buffer changed since on-type formatting request {}, dropping edits",
+                request_id
+            );
+            return Ok(());
+        }
+
+        self.apply_lsp_text_edits(buffer_id, edits)?;
+        Ok(())
+    }
+
     /// Apply LSP text edits to a buffer and return the number of changes made.
     /// Edits are sorted in reverse order and applied as a batch.
     pub(crate) fn apply_lsp_text_edits(
@@ -1157,6 +1451,7 @@ impl Editor {
         // Collect all events for this buffer into a batch
         let mut batch_events = Vec::new();
         let mut changes = 0;
+        let encoding = self.lsp_position_encoding(buffer_id);
 
         // Create events for all edits
         for edit in edits {
@@ -1171,8 +1466,12 @@ impl Editor {
             let end_line = edit.range.end.line as usize;
             let end_char = edit.range.end.character as usize;
 
-            let start_pos = state.buffer.lsp_position_to_byte(start_line, start_char);
-            let end_pos = state.buffer.lsp_position_to_byte(end_line, end_char);
+            let start_pos = state
+                .buffer
+                .lsp_position_to_byte(start_line, start_char, encoding);
+            let end_pos = state
+                .buffer
+                .lsp_position_to_byte(end_line, end_char, encoding);
             let buffer_len = state.buffer.len();
 
             // Log the conversion for debugging
@@ -1226,137 +1525,24 @@ impl Editor {
                 buffer_id,
                 batch_events,
                 "LSP Rename".to_string(),
+                None,
             )?;
         }
 
         Ok(changes)
     }
 
-    /// Handle rename response from LSP
-    pub fn handle_rename_response(
-        &mut self,
-        _request_id: u64,
-        result: Result<lsp_types::WorkspaceEdit, String>,
-    ) -> io::Result<()> {
-        self.lsp_status.clear();
-
-        match result {
-            Ok(workspace_edit) => {
-                // Log the full workspace edit for debugging
-                tracing::debug!(
-                    "Received WorkspaceEdit: changes={:?}, document_changes={:?}",
-                    workspace_edit.changes.as_ref().map(|c| c.len()),
-                    workspace_edit.document_changes.as_ref().map(|dc| match dc {
-                        lsp_types::DocumentChanges::Edits(e) => format!("{} edits", e.len()),
-                        lsp_types::DocumentChanges::Operations(o) =>
-                            format!("{} operations", o.len()),
-                    })
-                );
-
-                // Apply the workspace edit
-                let mut total_changes = 0;
-
-                // Handle changes (map of URI -> Vec<TextEdit>)
-                if let Some(changes) = workspace_edit.changes {
-                    for (uri, edits) in changes {
-                        if let Ok(path) = uri_to_path(&uri) {
-                            let buffer_id = self.open_file(&path)?;
-                            total_changes += self.apply_lsp_text_edits(buffer_id, edits)?;
-                        }
-                    }
-                }
-
-                // Handle document_changes (TextDocumentEdit[])
-                // This is what rust-analyzer sends instead of changes
-                if let Some(document_changes) = workspace_edit.document_changes {
-                    use lsp_types::DocumentChanges;
-
-                    let text_edits = match document_changes {
-                        DocumentChanges::Edits(edits) => edits,
-                        DocumentChanges::Operations(ops) => {
-                            // Extract TextDocumentEdit from operations
-                            ops.into_iter()
-                                .filter_map(|op| {
-                                    if let lsp_types::DocumentChangeOperation::Edit(edit) = op {
-                                        Some(edit)
-                                    } else {
-                                        None
-                                    }
-                                })
-                                .collect()
-                        }
-                    };
-
-                    for text_doc_edit in text_edits {
-                        let uri = text_doc_edit.text_document.uri;
-
-                        if let Ok(path) = uri_to_path(&uri) {
-                            let buffer_id = self.open_file(&path)?;
-
-                            // Extract TextEdit from OneOf<TextEdit, AnnotatedTextEdit>
-                            let edits: Vec<lsp_types::TextEdit> = text_doc_edit
-                                .edits
-                                .into_iter()
-                                .map(|one_of| match one_of {
-                                    lsp_types::OneOf::Left(text_edit) => text_edit,
-                                    lsp_types::OneOf::Right(annotated) => annotated.text_edit,
-                                })
-                                .collect();
-
-                            // Log the edits for debugging
-                            tracing::info!(
-                                "Applying {} edits from rust-analyzer for {:?}:",
-                                edits.len(),
-                                path
-                            );
-                            for (i, edit) in edits.iter().enumerate() {
-                                tracing::info!(
-                                    "  Edit {}: line {}:{}-{}:{} -> {:?}",
-                                    i,
-                                    edit.range.start.line,
-                                    edit.range.start.character,
-                                    edit.range.end.line,
-                                    edit.range.end.character,
-                                    edit.new_text
-                                );
-                            }
-
-                            total_changes += self.apply_lsp_text_edits(buffer_id, edits)?;
-                        }
-                    }
-                }
-
-                self.status_message = Some(t!("lsp.renamed", count = total_changes).to_string());
-            }
-            Err(error) => {
-                // Per LSP spec: ContentModified errors (-32801) should NOT be shown to user
-                // These are expected when document changes during LSP operations
-                // Reference: https://github.com/neovim/neovim/issues/16900
-                if error.contains("content modified") || error.contains("-32801") {
-                    tracing::debug!(
-                        "LSP rename: ContentModified error (expected, ignoring): {}",
-                        error
-                    );
-                    self.status_message = Some(t!("lsp.rename_cancelled").to_string());
-                } else {
-                    // Show other errors to user
-                    self.status_message = Some(t!("lsp.rename_failed", error = &error).to_string());
-                }
-            }
-        }
-
-        Ok(())
-    }
-
     /// Apply events to a specific buffer using bulk edit optimization (O(n) vs O(n²))
     ///
     /// This is similar to `apply_events_as_bulk_edit` but works on a specific buffer
     /// (which may not be the active buffer) and handles LSP notifications correctly.
+    /// The undo step is named `label` in undo messages, if given.
     pub(crate) fn apply_events_to_buffer_as_bulk_edit(
         &mut self,
         buffer_id: BufferId,
         events: Vec<Event>,
         description: String,
+        label: Option<String>,
     ) -> io::Result<()> {
         use crate::model::event::CursorId;
 
@@ -1364,19 +1550,7 @@ impl Editor {
             return Ok(());
         }
 
-        // Create a temporary batch for collecting LSP changes (before applying)
-        let batch_for_lsp = Event::Batch {
-            events: events.clone(),
-            description: description.clone(),
-        };
-
-        // IMPORTANT: Calculate LSP changes BEFORE applying to buffer!
-        // The byte positions in the events are relative to the ORIGINAL buffer.
-        let original_active = self.active_buffer();
-        self.split_manager.set_active_buffer_id(buffer_id);
-        let lsp_changes = self.collect_lsp_changes(&batch_for_lsp);
-        self.split_manager.set_active_buffer_id(original_active);
-
+        let encoding = self.lsp_position_encoding(buffer_id);
         let state = self
             .buffers
             .get_mut(&buffer_id)
@@ -1409,6 +1583,16 @@ impl Editor {
         // Sort edits by position descending (required by apply_bulk_edits)
         edits.sort_by(|a, b| b.0.cmp(&a.0));
 
+        // IMPORTANT: Calculate LSP changes BEFORE applying to buffer!
+        // The byte positions in the events are relative to the ORIGINAL buffer.
+        let lsp_changes = range_changes(
+            &state.buffer,
+            edits
+                .iter()
+                .map(|(pos, del, text)| (*pos..*pos + *del, text.as_str())),
+            encoding,
+        );
+
         // Convert to references for apply_bulk_edits
         let edit_refs: Vec<(usize, usize, &str)> = edits
             .iter()
@@ -1476,22 +1660,30 @@ impl Editor {
 
         // Add to event log
         if let Some(event_log) = self.event_logs.get_mut(&buffer_id) {
-            event_log.append(bulk_edit);
+            match label {
+                Some(label) => event_log.append_labeled(bulk_edit, label),
+                None => event_log.append(bulk_edit),
+            };
         }
 
         // Notify LSP about the changes using pre-calculated positions
-        self.send_lsp_changes_for_buffer(buffer_id, lsp_changes);
+        self.sync_lsp_changes(buffer_id, lsp_changes);
 
         Ok(())
     }
 
-    /// Send pre-calculated LSP changes for a specific buffer
+    /// Send pre-calculated LSP changes for a specific buffer to the servers
+    /// with labels `servers`
+    ///
+    /// A server that doesn't have the buffer open yet gets didOpen instead,
+    /// whose text already has the changes.
     pub(crate) fn send_lsp_changes_for_buffer(
         &mut self,
         buffer_id: BufferId,
         changes: Vec<TextDocumentContentChangeEvent>,
+        servers: &[String],
     ) {
-        if changes.is_empty() {
+        if changes.is_empty() || servers.is_empty() {
             return;
         }
 
@@ -1523,228 +1715,51 @@ impl Editor {
             }
         };
 
-        // Get the file path for language detection
-        let path = match metadata.file_path() {
-            Some(p) => p,
-            None => {
-                tracing::debug!("send_lsp_changes_for_buffer: no file path for buffer");
-                return;
-            }
-        };
-
-        let language = match detect_language(path, &self.config.languages) {
-            Some(l) => l,
-            None => {
-                tracing::debug!(
-                    "send_lsp_changes_for_buffer: no language detected for {:?}",
-                    path
-                );
-                return;
-            }
-        };
-
         tracing::trace!(
             "send_lsp_changes_for_buffer: sending {} changes to {} in single didChange notification",
             changes.len(),
             uri.as_str()
         );
 
-        // Get handle ID first
-        let handle_id = {
-            let Some(lsp) = self.lsp.as_mut() else {
-                tracing::debug!("send_lsp_changes_for_buffer: no LSP manager available");
-                return;
-            };
-            let Some(handle) = lsp.get_or_spawn(&language) else {
-                tracing::warn!(
-                    "send_lsp_changes_for_buffer: failed to get or spawn LSP client for {}",
-                    language
-                );
-                return;
-            };
-            handle.id()
-        };
-
-        // Check if didOpen needs to be sent first
-        let needs_open = {
-            let Some(metadata) = self.buffer_metadata.get(&buffer_id) else {
-                return;
-            };
-            !metadata.lsp_opened_with.contains(&handle_id)
-        };
-
-        if needs_open {
-            // Get text for didOpen
-            let text = match self
-                .buffers
-                .get(&buffer_id)
-                .and_then(|s| s.buffer.to_string())
-            {
-                Some(t) => t,
-                None => {
-                    tracing::debug!(
-                        "send_lsp_changes_for_buffer: buffer text not available for didOpen"
-                    );
+        for label in servers {
+            // Get handle ID first
+            let handle_id = {
+                let Some(lsp) = self.lsp.as_mut() else {
+                    tracing::debug!("send_lsp_changes_for_buffer: no LSP manager available");
                     return;
-                }
-            };
-
-            // Send didOpen first
-            if let Some(lsp) = self.lsp.as_mut() {
-                if let Some(handle) = lsp.get_or_spawn(&language) {
-                    if let Err(e) = handle.did_open(uri.clone(), text, language.clone()) {
-                        tracing::warn!("Failed to send didOpen before didChange: {}", e);
-                        return;
-                    }
-                    tracing::debug!(
-                        "Sent didOpen for {} to LSP handle {} before didChange",
-                        uri.as_str(),
-                        handle_id
+                };
+                let Some(handle) = lsp.get_or_spawn_server(label) else {
+                    tracing::warn!(
+                        "send_lsp_changes_for_buffer: failed to get or spawn LSP client for {}",
+                        label
                     );
-                }
-            }
+                    continue;
+                };
+                handle.id()
+            };
 
-            // Mark as opened
-            if let Some(metadata) = self.buffer_metadata.get_mut(&buffer_id) {
-                metadata.lsp_opened_with.insert(handle_id);
+            // Check if didOpen needs to be sent first
+            let needs_open = self
+                .buffer_metadata
+                .get(&buffer_id)
+                .is_some_and(|metadata| !metadata.lsp_opened_with.contains(&handle_id));
+            if needs_open {
+                let language = label_language(label).to_string();
+                self.open_buffer_with_lsp_server(buffer_id, &uri, &language, label, handle_id);
+                continue;
             }
-        }
 
-        // Now send didChange
-        if let Some(lsp) = &mut self.lsp {
-            if let Some(client) = lsp.get_or_spawn(&language) {
-                if let Err(e) = client.did_change(uri, changes) {
+            // Now send didChange
+            if let Some(client) = self.lsp.as_ref().and_then(|lsp| lsp.server(label)) {
+                if let Err(e) = client.did_change(uri.clone(), changes.clone()) {
                     tracing::warn!("Failed to send didChange to LSP: {}", e);
                 } else {
-                    tracing::trace!("Successfully sent batched didChange to LSP");
+                    tracing::trace!("Successfully sent batched didChange to LSP {}", label);
                 }
             }
         }
     }
 
-    /// Start rename mode - select the symbol at cursor and allow inline editing
-    pub(crate) fn start_rename(&mut self) -> io::Result<()> {
-        use crate::primitives::word_navigation::{find_word_end, find_word_start};
-
-        // Get the current buffer and cursor position
-        let (word_start, word_end) = {
-            let state = self.active_state();
-            let cursor_pos = state.cursors.primary().position;
-
-            // Find the word boundaries
-            let word_start = find_word_start(&state.buffer, cursor_pos);
-            let word_end = find_word_end(&state.buffer, cursor_pos);
-
-            // Check if we're on a word
-            if word_start >= word_end {
-                self.status_message = Some(t!("lsp.no_symbol_at_cursor").to_string());
-                return Ok(());
-            }
-
-            (word_start, word_end)
-        };
-
-        // Get the word text
-        let word_text = self.active_state_mut().get_text_range(word_start, word_end);
-
-        // Create an overlay to highlight the symbol being renamed
-        let overlay_handle = self.add_overlay(
-            None,
-            word_start..word_end,
-            crate::model::event::OverlayFace::Background {
-                color: (50, 100, 200), // Blue background for rename
-            },
-            100,
-            Some(t!("lsp.popup_renaming").to_string()),
-        );
-
-        // Enter rename mode using the Prompt system
-        // Store the rename metadata in the PromptType and pre-fill the input with the current name
-        let mut prompt = Prompt::new(
-            "Rename to: ".to_string(),
-            PromptType::LspRename {
-                original_text: word_text.clone(),
-                start_pos: word_start,
-                end_pos: word_end,
-                overlay_handle,
-            },
-        );
-        // Pre-fill the input with the current name and position cursor at the end
-        prompt.set_input(word_text);
-
-        self.prompt = Some(prompt);
-        Ok(())
-    }
-
-    /// Cancel rename mode - removes overlay if the prompt was for LSP rename
-    pub(crate) fn cancel_rename_overlay(&mut self, handle: &crate::view::overlay::OverlayHandle) {
-        self.remove_overlay(handle.clone());
-    }
-
-    /// Perform the actual LSP rename request
-    pub(crate) fn perform_lsp_rename(
-        &mut self,
-        new_name: String,
-        original_text: String,
-        start_pos: usize,
-        overlay_handle: crate::view::overlay::OverlayHandle,
-    ) {
-        // Remove the overlay first
-        self.cancel_rename_overlay(&overlay_handle);
-
-        // Check if the name actually changed
-        if new_name == original_text {
-            self.status_message = Some(t!("lsp.name_unchanged").to_string());
-            return;
-        }
-
-        // Use the position from when we entered rename mode, NOT the current cursor position
-        // This ensures we send the rename request for the correct symbol even if cursor moved
-        let rename_pos = start_pos;
-
-        // Convert byte position to LSP position (line, UTF-16 code units)
-        // LSP uses UTF-16 code units for character offsets, not byte offsets
-        let state = self.active_state();
-        let (line, character) = state.buffer.position_to_lsp_position(rename_pos);
-        let buffer_id = self.active_buffer();
-        let request_id = self.next_lsp_request_id;
-
-        // Use helper to ensure didOpen is sent before the request
-        let sent = self
-            .with_lsp_for_buffer(buffer_id, |handle, uri, _language| {
-                let result = handle.rename(
-                    request_id,
-                    uri.clone(),
-                    line as u32,
-                    character as u32,
-                    new_name.clone(),
-                );
-                if result.is_ok() {
-                    tracing::info!(
-                        "Requested rename at {}:{}:{} to '{}'",
-                        uri.as_str(),
-                        line,
-                        character,
-                        new_name
-                    );
-                }
-                result.is_ok()
-            })
-            .unwrap_or(false);
-
-        if sent {
-            self.next_lsp_request_id += 1;
-            self.lsp_status = "LSP: rename...".to_string();
-        } else if self
-            .buffer_metadata
-            .get(&buffer_id)
-            .and_then(|m| m.file_path())
-            .is_none()
-        {
-            self.status_message = Some(t!("lsp.cannot_rename_unsaved").to_string());
-        }
-    }
-
     /// Request inlay hints for the active buffer (if enabled and LSP available)
     pub(crate) fn request_inlay_hints_for_active_buffer(&mut self) {
         if !self.config.editor.enable_inlay_hints {
@@ -1,36 +1,113 @@
+mod anchored_popup;
+mod ansi_colors;
 mod async_messages;
+mod auto_completion;
+mod auto_revert;
+mod batch_code_actions;
+mod buffer_compaction;
+mod buffer_compare;
 mod buffer_management;
+mod buffer_pins;
+mod cache_budget;
 mod clipboard;
+mod column_align;
+mod command_args;
+mod command_trust;
+mod config_persistence;
+pub mod cursor_shape;
+mod data_path_actions;
+mod dir_compare;
+mod distraction_free;
+mod dry_run;
+mod editor_log;
+mod event_replay;
+mod explorer_context_menu;
+mod explorer_refresh;
 mod file_explorer;
+mod file_loading;
 pub mod file_open;
 mod file_open_input;
 mod file_operations;
+mod file_permissions;
+mod file_saving;
+mod formatting;
+mod full_message;
+mod goto_offset;
+mod gutter_columns;
 mod help;
+mod inline_blame;
 mod input;
 mod input_dispatch;
+mod input_latency;
+mod insert_content;
+mod keyboard_shortcuts;
+mod linked_rename;
 mod lsp_actions;
+mod lsp_document_sync;
+mod lsp_fanout;
+mod lsp_rename;
 mod lsp_requests;
 mod menu_actions;
 mod menu_context;
+mod mouse_cursors;
 mod mouse_input;
+mod named_layouts;
+mod notifications;
 mod on_save_actions;
+mod open_path;
+mod panel_restore;
+mod path_watches;
+mod pending_edits;
+mod plugin_buffers;
+mod plugin_command_queue;
 mod plugin_commands;
+mod plugin_hook_loops;
+mod plugin_init;
+mod plugin_text_reads;
 mod popup_actions;
+mod progress;
+mod project_carry_over;
 mod prompt_actions;
+mod prose_motions;
+mod protected_files;
+mod recent_projects;
 mod recovery_actions;
+mod remote_files;
 mod render;
+mod revert_buffer;
+mod save_actions;
+mod search_case;
+mod selection_stats;
+mod self_update;
 pub mod session;
+mod session_ownership;
+mod session_snapshots;
 mod settings_actions;
+mod settings_archive;
 mod shell_command;
 mod split_actions;
+mod split_hooks;
+mod split_lock;
+mod state_migration;
 mod tab_drag;
 mod terminal;
+mod terminal_appearance;
+mod terminal_effects;
 mod terminal_input;
+mod text_transforms;
+mod theme_contrast;
+mod theme_reload;
+mod todos;
 mod toggle_actions;
 pub mod types;
 mod undo_actions;
+mod usage_stats;
+mod validation;
 mod view_actions;
+mod virtual_buffer_ingest;
 pub mod warning_domains;
+mod word_completion;
+mod workspace_diagnostics;
 
 use rust_i18n::t;
 use std::path::Component;
@@ -67,40 +144,50 @@ pub(crate) fn normalize_path(path: &std::path::Path) -> std::path::PathBuf {
     }
 }
 
+use self::lsp_fanout::LspAnswer;
 use self::types::{
     Bookmark, CachedLayout, EventLineInfo, InteractiveReplaceState, LspMessageEntry,
-    LspProgressInfo, MacroRecordingState, MouseState, SearchState, TabContextMenu,
-    DEFAULT_BACKGROUND_FILE,
+    LspProgressInfo, MacroRecordingState, MouseState, TabContextMenu, DEFAULT_BACKGROUND_FILE,
 };
 use crate::config::Config;
-use crate::config_io::DirectoryContext;
+use crate::config_io::{ConfigLayer, DirectoryContext};
 use crate::input::actions::action_to_events as convert_action_to_events;
 use crate::input::buffer_mode::ModeRegistry;
 use crate::input::command_registry::CommandRegistry;
 use crate::input::commands::Suggestion;
 use crate::input::keybindings::{Action, KeyContext, KeybindingResolver};
 use crate::input::position_history::PositionHistory;
+use crate::model::edit_batch::EditBatch;
 use crate::model::event::{Event, EventLog, SplitDirection, SplitId};
+#[cfg(feature = "plugins")]
+use crate::primitives::ansi_background::color_to_rgb;
 use crate::services::async_bridge::{AsyncBridge, AsyncMessage};
+use crate::services::async_queue::{self, AsyncQueue, QueueStats};
+use crate::services::command_trust::CommandGate;
 use crate::services::fs::{FsBackend, FsManager, LocalFsBackend};
-use crate::services::lsp::manager::{detect_language, LspManager};
+use crate::services::lsp::diagnostic_store::DiagnosticStore;
+use crate::services::lsp::document_sync::{range_changes, DocumentChanges};
+use crate::services::lsp::manager::{detect_language, label_language, label_server, LspManager};
 #[cfg(feature = "plugins")]
 use crate::services::plugins::api::BufferSavedDiff;
 use crate::services::plugins::api::PluginCommand;
+use crate::services::plugins::api::PluginCommandTarget;
 use crate::services::plugins::PluginManager;
+use crate::services::recent_projects::RecentProjects;
 use crate::services::recovery::{RecoveryConfig, RecoveryService};
+use crate::services::startup_profile;
 use crate::services::time_source::{RealTimeSource, SharedTimeSource};
+use crate::services::worker_pool::WorkerPool;
 use crate::state::EditorState;
 use crate::types::LspServerConfig;
-use crate::view::file_tree::{FileTree, FileTreeView};
+use crate::view::file_tree::{FileTree, FileTreeView, NodeId, TreeSort};
 use crate::view::prompt::{Prompt, PromptType};
 use crate::view::scroll_sync::ScrollSyncManager;
-use crate::view::split::{SplitManager, SplitViewState};
+use crate::view::split::{search_namespace, SplitManager, SplitViewState};
 use crate::view::ui::{
     FileExplorerRenderer, SplitRenderer, StatusBarRenderer, SuggestionsRenderer,
 };
 use crossterm::event::{KeyCode, KeyModifiers};
-use lsp_types::{Position, Range as LspRange, TextDocumentContentChangeEvent};
 use ratatui::{
     layout::{Constraint, Direction, Layout},
     Frame,
@@ -119,14 +206,13 @@ pub use self::warning_domains::{
 };
 pub use crate::model::event::BufferId;
 
-/// Helper function to convert lsp_types::Uri to PathBuf
-fn uri_to_path(uri: &lsp_types::Uri) -> Result<PathBuf, String> {
-    // Convert to url::Url for path conversion
-    url::Url::parse(uri.as_str())
-        .map_err(|e| format!("Failed to parse URI: {}", e))?
-        .to_file_path()
-        .map_err(|_| "URI is not a file path".to_string())
-}
+pub use self::batch_code_actions::BatchScope;
+pub use self::buffer_compaction::BufferMemory;
+pub use self::event_replay::RecordedKeystroke;
+pub use self::explorer_refresh::ExplorerRefreshStats;
+pub use self::plugin_buffers::BufferStoreStats;
+pub use self::project_carry_over::ProjectCarryOver;
+pub use self::session_ownership::SessionOwnership;
 
 /// The main editor struct - manages multiple buffers, clipboard, and rendering
 pub struct Editor {
@@ -154,6 +240,9 @@ pub struct Editor {
     /// Active theme
     theme: crate::view::theme::Theme,
 
+    /// Text colors adjusted for `ui.minimum_contrast`
+    contrast_cache: crate::view::theme::ContrastCache,
+
     /// Optional ANSI background image
     ansi_background: Option<crate::primitives::ansi_background::AnsiBackground>,
 
@@ -176,6 +265,10 @@ pub struct Editor {
     /// This is used by Open Folder to do a clean context switch
     restart_with_dir: Option<PathBuf>,
 
+    /// If set, the editor should quit and run this binary, installed by a
+    /// self-update
+    restart_exe: Option<PathBuf>,
+
     /// Status message (shown in status bar)
     status_message: Option<String>,
 
@@ -204,6 +297,12 @@ pub struct Editor {
     /// Bridge for async messages from tokio tasks to main loop
     async_bridge: Option<AsyncBridge>,
 
+    /// Messages received from the bridge, waiting to be handled
+    async_queue: AsyncQueue,
+
+    /// Worker threads for background computation (results arrive via the async bridge)
+    worker_pool: WorkerPool,
+
     /// Split view manager
     split_manager: SplitManager,
 
@@ -234,6 +333,18 @@ pub struct Editor {
     /// When true, we still render the file explorer area even if file_explorer is temporarily None
     file_explorer_sync_in_progress: bool,
 
+    /// Path to reveal once the file explorer is back (requested while it was
+    /// being created or busy with another reveal)
+    pending_file_explorer_reveal: Option<PathBuf>,
+
+    /// Missing files opened with `files.missing_files: "ask"` waiting for
+    /// their "create it?" prompt
+    missing_file_prompts: open_path::MissingFilePrompts,
+
+    /// File explorer selection last reported through the
+    /// file_explorer_selection_changed hook
+    previous_file_explorer_selection: Option<PathBuf>,
+
     /// File explorer width as percentage (0.0 to 1.0)
     /// This is the runtime value that can be modified by dragging the border
     file_explorer_width_percent: f32,
@@ -245,6 +356,9 @@ pub struct Editor {
     /// When true, the menu bar will be hidden again when the menu is closed
     menu_bar_auto_shown: bool,
 
+    /// Layout to restore when leaving distraction-free mode (Some while it is on)
+    distraction_free: Option<distraction_free::DistractionFreeState>,
+
     /// Whether mouse capture is enabled
     mouse_enabled: bool,
 
@@ -305,6 +419,17 @@ pub struct Editor {
     /// Pending LSP inlay hints request ID (if any)
     pending_inlay_hints_request: Option<u64>,
 
+    /// Pending LSP on-type formatting request: (request ID, buffer, event log
+    /// index when requested)
+    pending_on_type_formatting_request: Option<(u64, BufferId, usize)>,
+
+    /// Pending LSP prepareRename request: (request ID, buffer, event log index
+    /// when requested)
+    pending_prepare_rename_request: Option<(u64, BufferId, usize)>,
+
+    /// Pending LSP rename request
+    pending_rename_request: Option<lsp_rename::PendingRename>,
+
     /// Hover symbol range (byte offsets) - for highlighting the symbol under hover
     /// Format: (start_byte_offset, end_byte_offset)
     hover_symbol_range: Option<(usize, usize)>,
@@ -316,11 +441,8 @@ pub struct Editor {
     /// Set when a mouse-triggered hover request is sent
     mouse_hover_screen_position: Option<(u16, u16)>,
 
-    /// Search state (if search is active)
-    search_state: Option<SearchState>,
-
-    /// Search highlight namespace (for efficient bulk removal)
-    search_namespace: crate::view::overlay::OverlayNamespace,
+    /// Plugin popup anchored to text and `mouse_moved_in_buffer` throttling
+    anchored_popup: anchored_popup::AnchoredPopupState,
 
     /// LSP diagnostic namespace (for filtering and bulk removal)
     lsp_diagnostic_namespace: crate::view::overlay::OverlayNamespace,
@@ -358,6 +480,25 @@ pub struct Editor {
     /// Maps panel ID (e.g., "diagnostics") to buffer ID
     panel_ids: HashMap<String, BufferId>,
 
+    /// Panels that are saved with the session, keyed by their buffer
+    persistent_panels: HashMap<BufferId, panel_restore::PersistentPanel>,
+
+    /// Buffers created by plugins, closed once hidden and unused for long
+    plugin_buffers: HashMap<BufferId, plugin_buffers::PluginBuffer>,
+
+    /// Parsed content of virtual buffers created with `parse_ansi`
+    ansi_entry_caches: HashMap<BufferId, ansi_colors::AnsiEntryCache>,
+
+    /// Virtual buffer content sent by plugins still to be taken in, see
+    /// [`virtual_buffer_ingest`]
+    pending_virtual_content: HashMap<
+        BufferId,
+        std::collections::VecDeque<crate::primitives::text_property::TextPropertyEntry>,
+    >,
+
+    /// File buffers showing their ANSI colors ("Interpret ANSI Colors")
+    ansi_file_views: HashMap<BufferId, ansi_colors::AnsiFileView>,
+
     /// Search history (for search and find operations)
     search_history: crate::input::input_history::InputHistory,
 
@@ -377,13 +518,12 @@ pub struct Editor {
     /// LSP log messages (recent messages from window/logMessage)
     lsp_log_messages: Vec<LspMessageEntry>,
 
-    /// Diagnostic result IDs per URI (for incremental pull diagnostics)
-    /// Maps URI string to last result_id received from server
-    diagnostic_result_ids: HashMap<String, String>,
+    /// Diagnostic result IDs per server and URI (for incremental pull diagnostics)
+    /// Maps server label and URI string to last result_id received from server
+    diagnostic_result_ids: HashMap<(String, String), String>,
 
-    /// Stored LSP diagnostics per URI
-    /// Maps file URI string to Vec of diagnostics for that file
-    stored_diagnostics: HashMap<String, Vec<lsp_types::Diagnostic>>,
+    /// Stored LSP diagnostics of the workspace, indexed by file
+    stored_diagnostics: DiagnosticStore,
 
     /// Event broadcaster for control events (observable by external systems)
     event_broadcaster: crate::model::control_event::EventBroadcaster,
@@ -393,6 +533,9 @@ pub struct Editor {
 
     /// Global search options (persist across searches)
     search_case_sensitive: bool,
+    /// Case matching of the current search flipped with the toggle while
+    /// `search.smart_case` is on; reset by the next search
+    search_case_override: Option<bool>,
     search_whole_word: bool,
     search_use_regex: bool,
     /// Whether to confirm each replacement (interactive/query-replace mode)
@@ -422,6 +565,14 @@ pub struct Editor {
     /// Stores the keys pressed so far in a chord sequence
     chord_state: Vec<(crossterm::event::KeyCode, crossterm::event::KeyModifiers)>,
 
+    /// The last key and the input layer that consumed it, for "Show Input
+    /// Layers" (see `input::focus_stack`)
+    last_key_layer: Option<(String, crate::input::focus_stack::InputLayer)>,
+
+    /// In-process hooks run around every action; a "pre_command" hook
+    /// returning false vetoes the action (see `input::pipeline`)
+    action_hooks: crate::services::plugins::hooks::HookRegistry,
+
     /// Pending LSP confirmation - language name awaiting user confirmation
     /// When Some, a confirmation popup is shown asking user to approve LSP spawn
     pending_lsp_confirmation: Option<String>,
@@ -433,12 +584,46 @@ pub struct Editor {
     /// Whether auto-revert mode is enabled (automatically reload files when changed on disk)
     auto_revert_enabled: bool,
 
+    /// Whether invariants are checked after every event (see `validation`)
+    validate_invariants: bool,
+
     /// Last time we polled for file changes (for auto-revert)
     last_auto_revert_poll: std::time::Instant,
 
     /// Last time we polled for directory changes (for file tree refresh)
     last_file_tree_poll: std::time::Instant,
 
+    /// Paths and globs watched for plugins, by watch id
+    path_watches: HashMap<u32, path_watches::PluginPathWatch>,
+
+    /// Last time we polled the plugins' path watches
+    last_path_watch_poll: std::time::Instant,
+
+    /// Chunked buffer reads of plugins in progress, by read id
+    buffer_chunk_reads: HashMap<u32, plugin_text_reads::BufferChunkRead>,
+
+    /// Buffer text sent to each plugin, by plugin name
+    plugin_text_transfers: HashMap<String, plugin_text_reads::PluginTextTransfer>,
+
+    /// Hook handlers of each plugin cut off for setting themselves off, by
+    /// plugin name
+    plugin_hook_loop_trips: HashMap<String, u64>,
+
+    /// Insert Command Output command still running
+    pending_command_output: Option<insert_content::PendingCommandOutput>,
+
+    /// Insert Command Output commands started, for their request ids
+    command_output_runs: u64,
+
+    /// Watch of the active theme's file, None for hardcoded themes
+    theme_watch: Option<theme_reload::ThemeWatch>,
+
+    /// Last time we polled the theme file
+    last_theme_poll: std::time::Instant,
+
+    /// Whether the terminal should be cleared before the next frame
+    full_redraw_requested: bool,
+
     /// Last known modification times for open files (for auto-revert)
     /// Maps file path to last known modification time
     file_mod_times: HashMap<PathBuf, std::time::SystemTime>,
@@ -451,6 +636,9 @@ pub struct Editor {
     /// Maps file path to (last event time, event count)
     file_rapid_change_counts: HashMap<PathBuf, (std::time::Instant, u32)>,
 
+    /// `.gitattributes` lookup for save conventions, refreshed by file change polling
+    git_attributes: crate::services::gitattributes::GitAttributes,
+
     /// File open dialog state (when PromptType::OpenFile is active)
     file_open_state: Option<file_open::FileOpenState>,
 
@@ -460,6 +648,9 @@ pub struct Editor {
     /// Recovery service for auto-save and crash recovery
     recovery_service: RecoveryService,
 
+    /// Recovery IDs of untitled buffers (file-backed buffers are keyed by path)
+    untitled_recovery_ids: HashMap<BufferId, String>,
+
     /// Time source for testable time operations
     time_source: SharedTimeSource,
 
@@ -474,6 +665,21 @@ pub struct Editor {
     /// When set, this mode's keybindings take precedence over normal key handling
     editor_mode: Option<String>,
 
+    /// Cursor style set by a plugin, overriding the configured styles
+    plugin_cursor_style: Option<crate::config::CursorStyle>,
+
+    /// Last cursor style written to the terminal
+    cursor_shape: cursor_shape::CursorShapeTracker,
+
+    /// Escape sequences to write before the next frame
+    terminal_effects: crate::view::terminal_effects::TerminalEffects,
+
+    /// Which of the light and dark themes to show, by the terminal's background
+    terminal_appearance: terminal_appearance::TerminalAppearance,
+
+    /// Overlay namespaces kept by "Revert Buffer to Saved" (all others are cleared)
+    revert_stable_namespaces: HashSet<crate::view::overlay::OverlayNamespace>,
+
     /// Warning log receiver and path (for tracking warnings)
     warning_log: Option<(std::sync::mpsc::Receiver<()>, PathBuf)>,
 
@@ -484,6 +690,120 @@ pub struct Editor {
     /// Periodic update checker (checks for new releases every hour)
     update_checker: Option<crate::services::release_checker::PeriodicUpdateChecker>,
 
+    /// Self-update downloading or installing a new release
+    self_update: Option<self_update::PendingSelfUpdate>,
+
+    /// Notification offering to restart into an installed update, and the
+    /// binary to run
+    self_update_restart: Option<(String, PathBuf)>,
+
+    /// Long operations in progress, of plugins and the editor
+    progress: crate::view::progress::ProgressList,
+
+    /// Spinner frame of the progress entries last drawn
+    progress_frame: usize,
+
+    /// Progress entries shown as notifications, and whether theirs has a
+    /// Cancel button
+    progress_notifications: HashMap<u32, bool>,
+
+    /// Where file contents are read from when opening files
+    file_source: Arc<dyn crate::services::file_loader::FileSource>,
+
+    /// How long opening a file waits for its contents before showing a loading placeholder
+    quick_load_timeout: std::time::Duration,
+
+    /// Buffers whose file contents are still loading or failed to load
+    file_loads: HashMap<BufferId, file_loading::BufferLoad>,
+
+    /// Where file contents are written to when saving
+    file_sink: Arc<dyn crate::services::file_saver::FileSink>,
+
+    /// How long saving waits for the write before continuing it in the background
+    quick_save_timeout: std::time::Duration,
+
+    /// Buffers whose file is being written in the background
+    file_saves: HashMap<BufferId, file_saving::PendingSave>,
+
+    /// Failed-save notifications offering a retry, by notification id
+    save_retries: HashMap<String, BufferId>,
+
+    /// Buffers being formatted
+    formats: HashMap<BufferId, formatting::PendingFormat>,
+
+    /// Buffers to run their file-mode formatter on once they are saved
+    format_after_save: HashSet<BufferId>,
+
+    /// Save actions running on the active buffer
+    save_actions: Option<save_actions::SaveActionRun>,
+
+    /// Id of the next `plugin:` save action request
+    next_save_action_request: u32,
+
+    /// Code action kinds each initialized language server advertises, by
+    /// server label
+    lsp_code_action_kinds: HashMap<String, Vec<String>>,
+
+    /// Code action being run on many files (see batch_code_actions.rs)
+    batch_code_action: Option<batch_code_actions::BatchCodeActionRun>,
+
+    /// Two buffers shown side by side by "Compare Buffer With…"
+    buffer_comparison: Option<buffer_compare::BufferComparison>,
+
+    /// Providers of remote paths replacing the built-in ones, by URL scheme
+    /// (used by tests to simulate remote hosts)
+    file_providers: HashMap<String, Arc<dyn crate::services::file_provider::FileProvider>>,
+
+    /// Open listings of remote directories
+    remote_dirs: HashMap<BufferId, remote_files::RemoteDirListing>,
+
+    /// The action being handled is a dry run (see dry_run.rs)
+    dry_run: bool,
+
+    /// Values of the parameters of the plugin command being run (see
+    /// command_args.rs)
+    command_args: Option<serde_json::Map<String, serde_json::Value>>,
+
+    /// When quitting gives up waiting for background saves, while it waits
+    quit_after_saves: Option<std::time::Instant>,
+
+    /// Recovery snapshots being written in the background
+    recovery_writes: HashMap<BufferId, crate::services::file_saver::FileSave>,
+
+    /// Result of the session save being written in the background
+    session_write: Option<
+        std::sync::mpsc::Receiver<
+            Result<crate::session::SessionWriteSummary, crate::session::SessionError>,
+        >,
+    >,
+
+    /// File explorer directories whose entries are still being listed
+    dir_loads: HashMap<NodeId, crate::services::dir_loader::DirLoad>,
+
+    /// Directories whose entry count badge is being computed
+    pending_entry_counts: HashSet<PathBuf>,
+
+    /// Explorer entries whose metadata is being fetched
+    pending_entry_metadata: HashSet<PathBuf>,
+
+    /// Whether the file explorer shows size and modified time columns
+    file_explorer_details: bool,
+
+    /// Order of the file explorer's entries, kept when the tree is rebuilt
+    file_explorer_sort: TreeSort,
+
+    /// Changes on disk waiting to be applied to the file explorer
+    explorer_refresh: explorer_refresh::ExplorerRefresh,
+
+    /// Memory budget shared by the highlight caches and retained views
+    cache_budget: cache_budget::CacheBudgetState,
+
+    /// When buffers were last touched, for compacting idle ones
+    buffer_compaction: buffer_compaction::BufferCompactionState,
+
+    /// Split changes held back from plugins during drags and layout restores
+    split_hooks: split_hooks::SplitHookState,
+
     /// Terminal manager for built-in terminal support
     terminal_manager: crate::services::terminal::TerminalManager,
 
@@ -531,6 +851,102 @@ pub struct Editor {
 
     /// Stdin streaming state (if reading from stdin)
     stdin_streaming: Option<StdinStreamingState>,
+
+    /// Open pending edits review (multi-file replace, plugin edit sets)
+    pending_edits_review: Option<pending_edits::PendingEditsReview>,
+
+    /// Open TODO list (project-wide keyword comments)
+    todo_panel: Option<todos::TodoPanel>,
+
+    /// Open directory tree comparison
+    dir_compare_panel: Option<dir_compare::DirComparePanel>,
+
+    /// Open workspace diagnostics panel
+    workspace_diagnostics: Option<workspace_diagnostics::WorkspaceDiagnosticsPanel>,
+
+    /// Rename in buffer in progress (linked editing of a word's occurrences)
+    linked_rename: Option<linked_rename::LinkedRename>,
+
+    /// Inline blame state of buffers that have it on
+    inline_blame: HashMap<BufferId, inline_blame::InlineBlame>,
+
+    /// Blames made this session, by file, for toggling inline blame again
+    blame_cache: HashMap<PathBuf, crate::services::git_blame::BlameSnapshot>,
+
+    /// Plugin commands waiting for their buffer or split to be created
+    plugin_command_queue: plugin_command_queue::PluginCommandQueue,
+
+    /// Open keyboard shortcuts view
+    keyboard_shortcuts_panel: Option<keyboard_shortcuts::KeyboardShortcutsPanel>,
+
+    /// Where the last paste went, for cycle paste
+    last_paste: Option<clipboard::LastPaste>,
+
+    /// Input latency samples for the latency HUD and event log
+    input_latency: input_latency::InputLatencyTracker,
+
+    /// Where keystrokes go when event logging is enabled
+    keystroke_log: Option<event_replay::KeystrokeLog>,
+
+    /// Words of open buffers, indexed while idle, for completion
+    word_completion: word_completion::WordCompletion,
+
+    /// Completion opened by typing: the pending request and popup
+    auto_completion: auto_completion::AutoCompletion,
+
+    /// How each language server syncs documents, and whole texts due to them
+    lsp_document_sync: lsp_document_sync::LspDocumentSync,
+
+    /// Requests sent to several servers of a language, waiting to be merged
+    lsp_fanout: lsp_fanout::LspFanout,
+
+    /// Toast notifications and their history
+    notifications: crate::view::notifications::NotificationStack,
+
+    /// Config files as last loaded or written, for conflict detection on save
+    config_sync: config_persistence::ConfigSync,
+
+    /// Legacy state waiting on the migration popup
+    pending_state_migration: Option<state_migration::PendingStateMigration>,
+
+    /// Export checklist and import waiting on their popups
+    settings_archive: settings_archive::SettingsArchiveState,
+
+    /// Plugin gutter columns and the gutter layout
+    gutter_columns: gutter_columns::GutterColumns,
+
+    /// Plugin actions of the file explorer's context menu, and the open menu
+    explorer_context_menu: explorer_context_menu::ExplorerContextMenu,
+
+    /// The editor's log file and its viewer
+    editor_log: editor_log::EditorLogState,
+
+    /// Marker written while this instance owns the working directory's session
+    instance_marker: crate::session::InstanceMarker,
+
+    /// Whether this instance persists the session, see [`Editor::claim_session_ownership`]
+    session_ownership: SessionOwnership,
+
+    /// Session owned by another instance, waiting on the ownership popup
+    pending_session_ownership: Option<session_ownership::PendingSessionOwnership>,
+
+    /// Title and text of the "Show Full Message" popup, copied on Enter
+    full_message: Option<(String, String)>,
+
+    /// Decides whether commands named by the project's config may run
+    command_gate: CommandGate,
+
+    /// Recently used project directories, as last loaded
+    recent_projects: RecentProjects,
+
+    /// Project command waiting on the trust popup
+    pending_command_trust: Option<command_trust::PendingCommandTrust>,
+
+    /// Title of the open "Review blocked commands" popup
+    blocked_commands_review: Option<String>,
+
+    /// Usage statistics counted but not yet written
+    usage_stats: usage_stats::UsageStatsTracker,
 }
 
 /// State for tracking stdin streaming in background
@@ -565,11 +981,14 @@ impl Editor {
             dir_context,
             true,
             color_capability,
+            None,
         )
     }
 
     /// Create a new editor with an explicit working directory
     /// This is useful for testing with isolated temporary directories
+    /// The editor's timers run on `time_source`, or the real clock if None
+    #[allow(clippy::too_many_arguments)]
     pub fn with_working_dir(
         config: Config,
         width: u16,
@@ -578,7 +997,15 @@ impl Editor {
         dir_context: DirectoryContext,
         plugins_enabled: bool,
         color_capability: crate::view::color_support::ColorCapability,
+        time_source: Option<SharedTimeSource>,
     ) -> io::Result<Self> {
+        let _phase = startup_profile::phase("editor");
+        let grammars_phase = startup_profile::phase("grammars");
+        let grammar_registry = crate::primitives::grammar_registry::GrammarRegistry::for_editor(
+            &dir_context.grammars_dir(),
+        );
+        startup_profile::count("syntaxes", grammar_registry.available_syntaxes().len());
+        drop(grammars_phase);
         Self::with_options(
             config,
             width,
@@ -587,9 +1014,9 @@ impl Editor {
             None,
             plugins_enabled,
             dir_context,
-            None,
+            time_source,
             color_capability,
-            crate::primitives::grammar_registry::GrammarRegistry::for_editor(),
+            grammar_registry,
         )
     }
 
@@ -646,8 +1073,20 @@ impl Editor {
         // This ensures consistent path comparisons throughout the editor
         let working_dir = working_dir.canonicalize().unwrap_or_else(|_| working_dir);
 
-        // Load theme from config
-        let theme = crate::view::theme::Theme::from_name(&config.theme);
+        // Load theme from config, for "auto" the dark one until the terminal
+        // says otherwise
+        let theme_phase = startup_profile::phase("theme");
+        let mut theme = crate::view::theme::Theme::from_name_in(
+            terminal_appearance::configured_theme_name(
+                &config,
+                crate::view::theme::Appearance::Dark,
+            ),
+            Some(&dir_context.themes_dir()),
+        );
+        if config.ui.transparent_background {
+            theme.make_background_transparent();
+        }
+        drop(theme_phase);
 
         tracing::info!(
             "Grammar registry has {} syntaxes",
@@ -676,9 +1115,7 @@ impl Editor {
         buffer_metadata.insert(buffer_id, BufferMetadata::new());
 
         // Initialize LSP manager with current working directory as root
-        let root_uri = url::Url::from_file_path(&working_dir)
-            .ok()
-            .and_then(|u| u.as_str().parse::<lsp_types::Uri>().ok());
+        let root_uri = crate::services::lsp::uri::path_to_uri(&working_dir);
 
         // Create Tokio runtime for async I/O (LSP, file watching, git, etc.)
         let tokio_runtime = tokio::runtime::Builder::new_multi_thread()
@@ -691,6 +1128,12 @@ impl Editor {
         // Create async bridge for communication
         let async_bridge = AsyncBridge::new();
 
+        // Background computation runs on the worker pool rather than ad-hoc threads
+        let worker_pool = WorkerPool::new(
+            WorkerPool::default_thread_count(),
+            Some(async_bridge.sender()),
+        );
+
         if tokio_runtime.is_none() {
             tracing::warn!("Failed to create Tokio runtime - async features disabled");
         }
@@ -708,6 +1151,10 @@ impl Editor {
             lsp.set_language_config(language.clone(), lsp_config.clone());
         }
 
+        // Servers, formatters and on-save actions named by the project ask first
+        let command_gate = CommandGate::load(&dir_context, &working_dir);
+        lsp.set_command_gate(command_gate.clone());
+
         // Initialize split manager with the initial buffer
         let split_manager = SplitManager::new(buffer_id);
 
@@ -727,80 +1174,48 @@ impl Editor {
         let command_registry = Arc::new(RwLock::new(CommandRegistry::new()));
 
         // Initialize plugin manager (handles both enabled and disabled cases internally)
-        let plugin_manager = PluginManager::new(enable_plugins, Arc::clone(&command_registry));
+        let runtime_phase = startup_profile::phase("plugin_runtime");
+        let plugin_manager = PluginManager::new(
+            enable_plugins,
+            Arc::clone(&command_registry),
+            Some(dir_context.transpile_cache_dir()),
+        );
+        drop(runtime_phase);
 
-        // Load TypeScript plugins from multiple directories:
-        // 1. Next to the executable (for cargo-dist installations)
-        // 2. In the working directory (for development/local usage)
-        // 3. From embedded plugins (for cargo-binstall, when embed-plugins feature is enabled)
+        let mut plugin_load_errors = Vec::new();
         if plugin_manager.is_active() {
-            let mut plugin_dirs: Vec<std::path::PathBuf> = vec![];
-
-            // Check next to executable first (for cargo-dist installations)
-            if let Ok(exe_path) = std::env::current_exe() {
-                if let Some(exe_dir) = exe_path.parent() {
-                    let exe_plugin_dir = exe_dir.join("plugins");
-                    if exe_plugin_dir.exists() {
-                        plugin_dirs.push(exe_plugin_dir);
-                    }
-                }
-            }
-
-            // Then check working directory (for development)
-            let working_plugin_dir = working_dir.join("plugins");
-            if working_plugin_dir.exists() && !plugin_dirs.contains(&working_plugin_dir) {
-                plugin_dirs.push(working_plugin_dir);
-            }
-
-            // If no disk plugins found, try embedded plugins (cargo-binstall builds)
-            #[cfg(feature = "embed-plugins")]
-            if plugin_dirs.is_empty() {
-                if let Some(embedded_dir) =
-                    crate::services::plugins::embedded::get_embedded_plugins_dir()
-                {
-                    tracing::info!("Using embedded plugins from: {:?}", embedded_dir);
-                    plugin_dirs.push(embedded_dir.clone());
-                }
-            }
-
-            if plugin_dirs.is_empty() {
-                tracing::debug!(
-                    "No plugins directory found next to executable or in working dir: {:?}",
-                    working_dir
-                );
-            }
-
-            // Load from all found plugin directories
-            for plugin_dir in plugin_dirs {
-                tracing::info!("Loading TypeScript plugins from: {:?}", plugin_dir);
-                let errors = plugin_manager.load_plugins_from_dir(&plugin_dir);
-                if !errors.is_empty() {
-                    for err in &errors {
-                        tracing::error!("TypeScript plugin load error: {}", err);
-                    }
-                    // In debug/test builds, panic to surface plugin loading errors
-                    #[cfg(debug_assertions)]
-                    panic!(
-                        "TypeScript plugin loading failed with {} error(s): {}",
-                        errors.len(),
-                        errors.join("; ")
-                    );
-                }
+            let _phase = startup_profile::phase("plugins");
+            plugin_load_errors = plugin_init::load_plugins(&plugin_manager, &working_dir);
+            #[cfg(feature = "plugins")]
+            if startup_profile::is_enabled() {
+                startup_profile::count("loaded", plugin_manager.list_plugins().len());
             }
         }
 
+        // Layout width calculations must match how the terminal draws ambiguous characters
+        crate::primitives::display_width::set_ambiguous_wide(
+            config.editor.ambiguous_width == crate::config::AmbiguousWidth::Wide,
+        );
+        crate::view::glyphs::configure(config.ui.ascii_only, &theme.glyphs);
+
         // Extract config values before moving config into the struct
         let file_explorer_width = config.file_explorer.width;
+        let file_explorer_details = config.file_explorer.show_details;
         let recovery_enabled = config.editor.recovery_enabled;
         let auto_save_interval_secs = config.editor.auto_save_interval_secs;
         let check_for_updates = config.check_for_updates;
+        let minimum_contrast = config.ui.minimum_contrast;
+        let cache_budget_mb = config.performance.cache_budget_mb;
+        let auto_revert_enabled = config.files.auto_revert != crate::config::AutoRevertMode::Off;
 
         // Start periodic update checker if enabled
         let update_checker = if check_for_updates {
             tracing::debug!("Update checking enabled, starting periodic checker");
             Some(
                 crate::services::release_checker::start_periodic_update_check(
+                    &worker_pool,
                     crate::services::release_checker::DEFAULT_RELEASES_URL,
+                    time_source.clone(),
                 ),
             )
         } else {
@@ -808,7 +1223,19 @@ impl Editor {
             None
         };
 
-        Ok(Editor {
+        let config_sync = config_persistence::ConfigSync::new(&dir_context, &working_dir, &config);
+
+        let recovery_service = RecoveryService::for_working_dir(
+            RecoveryConfig {
+                enabled: recovery_enabled,
+                auto_save_interval_secs,
+                ..RecoveryConfig::default()
+            },
+            dir_context.recovery_dir(),
+            Some(&working_dir),
+        );
+
+        let mut editor = Editor {
             buffers,
             event_logs,
             next_buffer_id: 1,
@@ -823,6 +1250,7 @@ impl Editor {
             clipboard: crate::services::clipboard::Clipboard::new(),
             should_quit: false,
             restart_with_dir: None,
+            restart_exe: None,
             status_message: None,
             plugin_status_message: None,
             prompt: None,
@@ -833,6 +1261,8 @@ impl Editor {
             mode_registry: ModeRegistry::new(),
             tokio_runtime,
             async_bridge: Some(async_bridge),
+            async_queue: AsyncQueue::new(),
+            worker_pool,
             split_manager,
             split_view_states,
             previous_viewports: HashMap::new(),
@@ -841,9 +1271,13 @@ impl Editor {
             fs_manager,
             file_explorer_visible: false,
             file_explorer_sync_in_progress: false,
+            pending_file_explorer_reveal: None,
+            missing_file_prompts: Default::default(),
+            previous_file_explorer_selection: None,
             file_explorer_width_percent: file_explorer_width,
             menu_bar_visible: true,
             menu_bar_auto_shown: false,
+            distraction_free: None,
             mouse_enabled: true,
             mouse_cursor_position: None,
             gpm_active: false,
@@ -863,13 +1297,13 @@ impl Editor {
             pending_signature_help_request: None,
             pending_code_actions_request: None,
             pending_inlay_hints_request: None,
+            pending_on_type_formatting_request: None,
+            pending_prepare_rename_request: None,
+            pending_rename_request: None,
             hover_symbol_range: None,
             hover_symbol_overlay: None,
             mouse_hover_screen_position: None,
-            search_state: None,
-            search_namespace: crate::view::overlay::OverlayNamespace::from_string(
-                "search".to_string(),
-            ),
+            anchored_popup: anchored_popup::AnchoredPopupState::default(),
             lsp_diagnostic_namespace: crate::view::overlay::OverlayNamespace::from_string(
                 "lsp-diagnostic".to_string(),
             ),
@@ -883,6 +1317,11 @@ impl Editor {
             plugin_manager,
             seen_byte_ranges: HashMap::new(),
             panel_ids: HashMap::new(),
+            persistent_panels: HashMap::new(),
+            plugin_buffers: HashMap::new(),
+            ansi_entry_caches: HashMap::new(),
+            pending_virtual_content: HashMap::new(),
+            ansi_file_views: HashMap::new(),
             search_history: {
                 // Load search history from disk if available
                 let path = dir_context.search_history_path();
@@ -908,10 +1347,11 @@ impl Editor {
             lsp_window_messages: Vec::new(),
             lsp_log_messages: Vec::new(),
             diagnostic_result_ids: HashMap::new(),
-            stored_diagnostics: HashMap::new(),
+            stored_diagnostics: DiagnosticStore::new(),
             event_broadcaster: crate::model::control_event::EventBroadcaster::default(),
             bookmarks: HashMap::new(),
             search_case_sensitive: true,
+            search_case_override: None,
             search_whole_word: false,
             search_use_regex: false,
             search_confirm_each: false,
@@ -923,31 +1363,80 @@ impl Editor {
             #[cfg(feature = "plugins")]
             plugin_render_requested: false,
             chord_state: Vec::new(),
+            last_key_layer: None,
+            action_hooks: crate::services::plugins::hooks::HookRegistry::new(),
             pending_lsp_confirmation: None,
             pending_close_buffer: None,
-            auto_revert_enabled: true,
+            auto_revert_enabled,
+            validate_invariants: validation::validation_requested(),
             last_auto_revert_poll: time_source.now(),
             last_file_tree_poll: time_source.now(),
+            path_watches: HashMap::new(),
+            last_path_watch_poll: time_source.now(),
+            buffer_chunk_reads: HashMap::new(),
+            plugin_text_transfers: HashMap::new(),
+            plugin_hook_loop_trips: HashMap::new(),
+            pending_command_output: None,
+            command_output_runs: 0,
+            theme_watch: None,
+            contrast_cache: crate::view::theme::ContrastCache::new(minimum_contrast),
+            last_theme_poll: time_source.now(),
+            full_redraw_requested: false,
             file_mod_times: HashMap::new(),
             dir_mod_times: HashMap::new(),
             file_rapid_change_counts: HashMap::new(),
+            git_attributes: crate::services::gitattributes::GitAttributes::new(),
             file_open_state: None,
             file_browser_layout: None,
-            recovery_service: {
-                let recovery_config = RecoveryConfig {
-                    enabled: recovery_enabled,
-                    auto_save_interval_secs,
-                    ..RecoveryConfig::default()
-                };
-                RecoveryService::with_config_and_dir(recovery_config, dir_context.recovery_dir())
-            },
+            recovery_service,
+            untitled_recovery_ids: HashMap::new(),
             time_source: time_source.clone(),
             last_auto_save: time_source.now(),
             active_custom_contexts: HashSet::new(),
             editor_mode: None,
+            plugin_cursor_style: None,
+            cursor_shape: Default::default(),
+            terminal_effects: Default::default(),
+            terminal_appearance: Default::default(),
+            revert_stable_namespaces: HashSet::new(),
             warning_log: None,
             warning_domains: WarningDomainRegistry::new(),
             update_checker,
+            self_update: None,
+            self_update_restart: None,
+            progress: Default::default(),
+            progress_frame: 0,
+            progress_notifications: HashMap::new(),
+            file_source: Arc::new(crate::services::file_loader::LocalFileSource),
+            quick_load_timeout: file_loading::QUICK_LOAD_TIMEOUT,
+            file_loads: HashMap::new(),
+            file_sink: Arc::new(crate::services::file_saver::LocalFileSink),
+            quick_save_timeout: file_saving::QUICK_SAVE_TIMEOUT,
+            file_saves: HashMap::new(),
+            save_retries: HashMap::new(),
+            formats: HashMap::new(),
+            format_after_save: HashSet::new(),
+            save_actions: None,
+            next_save_action_request: 1,
+            lsp_code_action_kinds: HashMap::new(),
+            batch_code_action: None,
+            buffer_comparison: None,
+            file_providers: HashMap::new(),
+            remote_dirs: HashMap::new(),
+            dry_run: false,
+            command_args: None,
+            quit_after_saves: None,
+            recovery_writes: HashMap::new(),
+            session_write: None,
+            dir_loads: HashMap::new(),
+            pending_entry_counts: HashSet::new(),
+            pending_entry_metadata: HashSet::new(),
+            file_explorer_details,
+            file_explorer_sort: TreeSort::default(),
+            explorer_refresh: Default::default(),
+            cache_budget: cache_budget::CacheBudgetState::new(cache_budget_mb),
+            buffer_compaction: Default::default(),
+            split_hooks: Default::default(),
             terminal_manager: crate::services::terminal::TerminalManager::new(),
             terminal_buffers: HashMap::new(),
             terminal_backing_files: HashMap::new(),
@@ -962,7 +1451,45 @@ impl Editor {
             stdin_streaming: None,
             review_hunks: Vec::new(),
             active_action_popup: None,
-        })
+            pending_edits_review: None,
+            todo_panel: None,
+            dir_compare_panel: None,
+            workspace_diagnostics: None,
+            linked_rename: None,
+            inline_blame: HashMap::new(),
+            blame_cache: HashMap::new(),
+            plugin_command_queue: plugin_command_queue::PluginCommandQueue::default(),
+            keyboard_shortcuts_panel: None,
+            last_paste: None,
+            input_latency: Default::default(),
+            keystroke_log: None,
+            word_completion: word_completion::WordCompletion::new(time_source.now()),
+            auto_completion: Default::default(),
+            lsp_document_sync: Default::default(),
+            lsp_fanout: Default::default(),
+            notifications: Default::default(),
+            config_sync,
+            pending_state_migration: None,
+            settings_archive: Default::default(),
+            gutter_columns: Default::default(),
+            explorer_context_menu: Default::default(),
+            editor_log: editor_log::EditorLogState::new(time_source.now()),
+            instance_marker: crate::session::InstanceMarker::for_new_instance(),
+            session_ownership: SessionOwnership::Owner,
+            pending_session_ownership: None,
+            full_message: None,
+            command_gate,
+            recent_projects: RecentProjects::load(&dir_context.recent_projects_path()),
+            pending_command_trust: None,
+            blocked_commands_review: None,
+            usage_stats: usage_stats::UsageStatsTracker::new(time_source.now()),
+        };
+        editor.apply_clipboard_limits();
+        editor.apply_gutter_columns();
+        editor.sync_theme_watch();
+        editor.report_plugin_init_failure();
+        editor.report_plugin_load_errors(plugin_load_errors);
+        Ok(editor)
     }
 
     /// Get a reference to the event broadcaster
@@ -975,6 +1502,28 @@ impl Editor {
         self.async_bridge.as_ref()
     }
 
+    /// Whether async messages are waiting for the next
+    /// [`Editor::process_async_messages`]
+    pub fn has_queued_async_messages(&self) -> bool {
+        !self.async_queue.is_empty()
+    }
+
+    /// Whether plugin hooks are waiting for [`Editor::dispatch_plugin_hooks`]
+    pub fn has_pending_plugin_hooks(&self) -> bool {
+        self.plugin_manager.has_pending_hooks()
+    }
+
+    /// Length of the async message queue and how much of it was coalesced
+    /// or dropped
+    pub fn async_queue_stats(&self) -> QueueStats {
+        self.async_queue.stats()
+    }
+
+    /// Get the worker pool for submitting background jobs
+    pub fn worker_pool(&self) -> &WorkerPool {
+        &self.worker_pool
+    }
+
     /// Get a reference to the config
     pub fn config(&self) -> &Config {
         &self.config
@@ -985,14 +1534,25 @@ impl Editor {
         &self.time_source
     }
 
+    /// Whether the terminal should be cleared before drawing, e.g. after the
+    /// theme changed. Resets the request.
+    pub fn take_full_redraw_request(&mut self) -> bool {
+        std::mem::take(&mut self.full_redraw_requested)
+    }
+
     /// Emit a control event
     pub fn emit_event(&self, name: impl Into<String>, data: serde_json::Value) {
         self.event_broadcaster.emit_named(name, data);
     }
 
     /// Send a response to a plugin for an async operation
-    fn send_plugin_response(&self, response: crate::services::plugins::api::PluginResponse) {
-        self.plugin_manager.deliver_response(response);
+    ///
+    /// While plugin commands are being processed, the response waits for the
+    /// end of the batch (see `plugin_command_queue`).
+    fn send_plugin_response(&mut self, response: crate::services::plugins::api::PluginResponse) {
+        if let Some(response) = self.plugin_command_queue.hold_response(response) {
+            self.plugin_manager.deliver_response(response);
+        }
     }
 
     /// Get all keybindings as (key, action) pairs
@@ -1048,6 +1608,18 @@ impl Editor {
         self.active_state().editing_disabled
     }
 
+    /// Hooks run around every action, whichever way it was triggered
+    ///
+    /// "pre_command" callbacks get [`HookArgs::PreCommand`] before the action
+    /// and can veto it by returning false; "post_command" callbacks get
+    /// [`HookArgs::PostCommand`] after it ran.
+    ///
+    /// [`HookArgs::PreCommand`]: crate::services::plugins::hooks::HookArgs::PreCommand
+    /// [`HookArgs::PostCommand`]: crate::services::plugins::hooks::HookArgs::PostCommand
+    pub fn action_hooks_mut(&mut self) -> &mut crate::services::plugins::hooks::HookRegistry {
+        &mut self.action_hooks
+    }
+
     /// Resolve a keybinding for the current mode
     ///
     /// First checks the global editor mode (for vi mode and other modal editing).
@@ -1088,13 +1660,26 @@ impl Editor {
             .collect()
     }
 
+    /// Shut down all language servers, waiting for them to exit
+    ///
+    /// Called when the editor loop ends, on quit and before restarting in
+    /// another working directory, since the servers' roots are then stale.
+    pub fn shutdown_lsp_servers(&mut self) {
+        if let Some(lsp) = self.lsp.as_mut() {
+            lsp.shutdown_all();
+        }
+    }
+
     /// Check if LSP server for a given language is running (ready)
-    pub fn is_lsp_server_ready(&self, language: &str) -> bool {
+    ///
+    /// `name` is a server label, or a language with several servers, any of
+    /// which being ready will do.
+    pub fn is_lsp_server_ready(&self, name: &str) -> bool {
         use crate::services::async_bridge::LspServerStatus;
-        self.lsp_server_statuses
-            .get(language)
-            .map(|status| matches!(status, LspServerStatus::Running))
-            .unwrap_or(false)
+        self.lsp_server_statuses.iter().any(|(label, status)| {
+            (label == name || label_language(label) == name)
+                && matches!(status, LspServerStatus::Running)
+        })
     }
 
     /// Get the LSP status string (displayed in status bar)
@@ -1103,8 +1688,8 @@ impl Editor {
     }
 
     /// Get stored LSP diagnostics (for testing and external access)
-    /// Returns a reference to the diagnostics map keyed by file URI
-    pub fn get_stored_diagnostics(&self) -> &HashMap<String, Vec<lsp_types::Diagnostic>> {
+    /// Returns the store of the diagnostics indexed by file
+    pub fn get_stored_diagnostics(&self) -> &DiagnosticStore {
         &self.stored_diagnostics
     }
 
@@ -1147,7 +1732,7 @@ impl Editor {
     /// Configure LSP server for a specific language
     pub fn set_lsp_config(&mut self, language: String, config: LspServerConfig) {
         if let Some(ref mut lsp) = self.lsp {
-            lsp.set_language_config(language, config);
+            lsp.set_language_config(language, config.into());
         }
     }
 
@@ -1170,22 +1755,6 @@ impl Editor {
         }
     }
 
-    /// Enable event log streaming to a file
-    pub fn enable_event_streaming<P: AsRef<Path>>(&mut self, path: P) -> io::Result<()> {
-        // Enable streaming for all existing event logs
-        for event_log in self.event_logs.values_mut() {
-            event_log.enable_streaming(&path)?;
-        }
-        Ok(())
-    }
-
-    /// Log keystroke for debugging
-    pub fn log_keystroke(&mut self, key_code: &str, modifiers: &str) {
-        if let Some(event_log) = self.event_logs.get_mut(&self.active_buffer()) {
-            event_log.log_keystroke(key_code, modifiers);
-        }
-    }
-
     /// Set up warning log monitoring
     ///
     /// When warnings/errors are logged, they will be written to the specified path
@@ -1211,7 +1780,10 @@ impl Editor {
 
         if new_warning_count > 0 {
             // Update general warning domain (don't auto-open file)
-            self.warning_domains.general.add_warnings(new_warning_count);
+            let now = self.time_source.now();
+            self.warning_domains
+                .general
+                .add_warnings(new_warning_count, now);
             self.warning_domains.general.set_log_path(path.clone());
         }
 
@@ -1233,6 +1805,8 @@ impl Editor {
         if let Some(path) = self.warning_domains.general.log_path.clone() {
             if let Err(e) = self.open_file(&path) {
                 tracing::error!("Failed to open warning log: {}", e);
+            } else if self.editor_log.has_log() {
+                self.set_status_message(t!("editor_log.warning_log_hint").to_string());
             }
         }
     }
@@ -1296,7 +1870,7 @@ impl Editor {
                 if self.mouse_state.lsp_hover_request_sent {
                     return false; // Already sent request for this position
                 }
-                if start_time.elapsed() < hover_delay {
+                if self.time_source.elapsed_since(start_time) < hover_delay {
                     return false; // Timer hasn't expired yet
                 }
                 Some((byte_pos, screen_x, screen_y))
@@ -1329,8 +1903,9 @@ impl Editor {
     /// and semantic highlights need to be recomputed.
     pub fn check_semantic_highlight_timer(&self) -> bool {
         // Check all buffers for pending semantic highlight redraws
+        let now = self.time_source.now();
         for state in self.buffers.values() {
-            if let Some(remaining) = state.semantic_highlight_cache.needs_redraw() {
+            if let Some(remaining) = state.semantic_highlight_cache.needs_redraw(now) {
                 if remaining.is_zero() {
                     return true;
                 }
@@ -1419,6 +1994,20 @@ impl Editor {
             self.key_context = crate::input::keybindings::KeyContext::Normal;
         }
 
+        // Keep the scroll position and layouts of the buffer being left, and
+        // bring back those of the one being shown if it hasn't changed since
+        let active_split = self.split_manager.active_split();
+        let revision_of = |id: BufferId| self.buffers.get(&id).map(|s| s.buffer.revision());
+        let (previous_revision, revision) = (revision_of(previous), revision_of(buffer_id));
+        if let Some(view_state) = self.split_view_states.get_mut(&active_split) {
+            if let Some(previous_revision) = previous_revision {
+                view_state.retain_view(previous, previous_revision);
+            }
+            if let Some(revision) = revision {
+                view_state.restore_view(buffer_id, revision);
+            }
+        }
+
         // Update split manager (single source of truth)
         self.split_manager.set_active_buffer_id(buffer_id);
 
@@ -1429,7 +2018,6 @@ impl Editor {
         }
 
         // Add buffer to the active split's open_buffers (tabs) if not already there
-        let active_split = self.split_manager.active_split();
         if let Some(view_state) = self.split_view_states.get_mut(&active_split) {
             view_state.add_buffer(buffer_id);
             // Update the previous buffer tracker
@@ -1539,6 +2127,16 @@ impl Editor {
             .viewport
     }
 
+    /// Get the line layout cache for the active split
+    pub fn active_line_layout(&self) -> &crate::view::ui::line_layout::LineLayoutCache {
+        let active_split = self.split_manager.active_split();
+        &self
+            .split_view_states
+            .get(&active_split)
+            .unwrap()
+            .line_layout
+    }
+
     /// Get the display name for a buffer (filename or virtual buffer name)
     pub fn get_buffer_display_name(&self, buffer_id: BufferId) -> String {
         self.buffer_metadata
@@ -1627,7 +2225,7 @@ impl Editor {
         if !in_interactive_replace {
             match event {
                 Event::Insert { .. } | Event::Delete { .. } | Event::BulkEdit { .. } => {
-                    self.clear_search_highlights();
+                    self.clear_searches_in_buffer(self.active_buffer());
                 }
                 Event::Batch { events, .. } => {
                     // Check if batch contains any Insert/Delete events
@@ -1635,7 +2233,7 @@ impl Editor {
                         .iter()
                         .any(|e| matches!(e, Event::Insert { .. } | Event::Delete { .. }));
                     if has_edits {
-                        self.clear_search_highlights();
+                        self.clear_searches_in_buffer(self.active_buffer());
                     }
                 }
                 _ => {}
@@ -1646,18 +2244,21 @@ impl Editor {
         self.trigger_plugin_hooks_for_event(event, line_info);
 
         // 4. Notify LSP of the change using pre-calculated positions
-        self.send_lsp_changes_for_buffer(self.active_buffer(), lsp_changes);
+        self.sync_lsp_changes(self.active_buffer(), lsp_changes);
     }
 
     /// Apply multiple Insert/Delete events efficiently using bulk edit optimization.
     ///
     /// This avoids O(n²) complexity by:
-    /// 1. Converting events to (position, delete_len, insert_text) tuples
+    /// 1. Collecting the events into an `EditBatch` of (position, delete_len, insert_text)
     /// 2. Applying all edits in a single tree pass via apply_bulk_edits
-    /// 3. Creating a BulkEdit event for undo (stores tree snapshot via Arc clone = O(1))
+    /// 3. Moving cursors and markers once, by the edits' summed shifts, and
+    ///    merging cursors that end up on top of each other
+    /// 4. Creating a BulkEdit event for undo (stores tree snapshot via Arc clone = O(1))
     ///
     /// # Arguments
-    /// * `events` - Vec of Insert/Delete events (sorted by position descending for correct application)
+    /// * `events` - Vec of Insert/Delete events, positions in the buffer before any of them,
+    ///   and MoveCursor events giving where cursors end up
     /// * `description` - Description for the undo log
     ///
     /// # Returns
@@ -1667,18 +2268,49 @@ impl Editor {
         events: Vec<Event>,
         description: String,
     ) -> Option<Event> {
-        use crate::model::event::CursorId;
+        self.apply_edit_batch_as_bulk_edit(EditBatch::from_events(&events), description)
+    }
 
-        // Check if any events modify the buffer
-        let has_buffer_mods = events
-            .iter()
-            .any(|e| matches!(e, Event::Insert { .. } | Event::Delete { .. }));
+    /// Apply the events of a multi-cursor action, as generated for each
+    /// cursor by `action_to_events`, as one bulk edit
+    ///
+    /// Unlike `apply_events_as_bulk_edit`, a cursor's MoveCursor is relative
+    /// to its own edits, the way applying the events in turn would take it.
+    pub fn apply_cursor_events_as_bulk_edit(
+        &mut self,
+        events: Vec<Event>,
+        description: String,
+    ) -> Option<Event> {
+        self.apply_edit_batch_as_bulk_edit(EditBatch::from_cursor_events(&events), description)
+    }
 
-        if !has_buffer_mods {
+    /// Apply a batch to the active buffer, returning its BulkEdit
+    fn apply_edit_batch_as_bulk_edit(
+        &mut self,
+        batch: EditBatch,
+        description: String,
+    ) -> Option<Event> {
+        use crate::model::event::CursorId;
+
+        if batch.is_empty() {
             // No buffer modifications - use regular Batch
             return None;
         }
 
+        // The edits are in descending order, so their LSP positions can be
+        // read from the buffer before any of them
+        let encoding = self.lsp_position_encoding(self.active_buffer());
+        let lsp_changes = range_changes(
+            &self.active_state().buffer,
+            batch.edits().iter().map(|edit| {
+                (
+                    edit.position..edit.position + edit.delete_len,
+                    edit.text.as_str(),
+                )
+            }),
+            encoding,
+        );
+
         let state = self.active_state_mut();
 
         // Capture old cursor states
@@ -1691,128 +2323,15 @@ impl Editor {
         // Snapshot the tree for undo (O(1) - Arc clone)
         let old_tree = state.buffer.snapshot_piece_tree();
 
-        // Convert events to edit tuples: (position, delete_len, insert_text)
-        // Events must be sorted by position descending (later positions first)
-        // This ensures earlier edits don't shift positions of later edits
-        let mut edits: Vec<(usize, usize, String)> = Vec::new();
-
-        for event in &events {
-            match event {
-                Event::Insert { position, text, .. } => {
-                    edits.push((*position, 0, text.clone()));
-                }
-                Event::Delete { range, .. } => {
-                    edits.push((range.start, range.len(), String::new()));
-                }
-                _ => {}
-            }
-        }
-
-        // Sort edits by position descending (required by apply_bulk_edits)
-        edits.sort_by(|a, b| b.0.cmp(&a.0));
-
-        // Convert to references for apply_bulk_edits
-        let edit_refs: Vec<(usize, usize, &str)> = edits
-            .iter()
-            .map(|(pos, del, text)| (*pos, *del, text.as_str()))
-            .collect();
-
-        // Apply bulk edits
-        let _delta = state.buffer.apply_bulk_edits(&edit_refs);
+        state.apply_edit_batch(&batch);
 
         // Snapshot the tree after edits (for redo) - O(1) Arc clone
         let new_tree = state.buffer.snapshot_piece_tree();
-
-        // Calculate new cursor positions based on events
-        // Process cursor movements from the original events
-        let mut new_cursors: Vec<(CursorId, usize, Option<usize>)> = old_cursors.clone();
-
-        // Calculate position adjustments from edits (sorted ascending by position)
-        // Each entry is (edit_position, delta) where delta = insert_len - delete_len
-        let mut position_deltas: Vec<(usize, isize)> = Vec::new();
-        for (pos, del_len, text) in &edits {
-            let delta = text.len() as isize - *del_len as isize;
-            position_deltas.push((*pos, delta));
-        }
-        position_deltas.sort_by_key(|(pos, _)| *pos);
-
-        // Helper: calculate cumulative shift for a position based on edits at lower positions
-        let calc_shift = |original_pos: usize| -> isize {
-            let mut shift: isize = 0;
-            for (edit_pos, delta) in &position_deltas {
-                if *edit_pos < original_pos {
-                    shift += delta;
-                }
-            }
-            shift
-        };
-
-        // Apply adjustments to cursor positions
-        // First check for explicit MoveCursor events (e.g., from indent operations)
-        // These take precedence over implicit cursor updates from Insert/Delete
-        for (cursor_id, ref mut pos, ref mut anchor) in &mut new_cursors {
-            let mut found_move_cursor = false;
-
-            // First pass: look for explicit MoveCursor events for this cursor
-            for event in &events {
-                if let Event::MoveCursor {
-                    cursor_id: event_cursor,
-                    new_position,
-                    new_anchor,
-                    ..
-                } = event
-                {
-                    if event_cursor == cursor_id {
-                        *pos = *new_position;
-                        *anchor = *new_anchor;
-                        found_move_cursor = true;
-                    }
-                }
-            }
-
-            // If no explicit MoveCursor, derive position from Insert/Delete
-            if !found_move_cursor {
-                for event in &events {
-                    match event {
-                        Event::Insert {
-                            position,
-                            text,
-                            cursor_id: event_cursor,
-                        } if event_cursor == cursor_id => {
-                            // For insert, cursor moves to end of inserted text
-                            // Account for shifts from edits at lower positions
-                            let shift = calc_shift(*position);
-                            let adjusted_pos = (*position as isize + shift) as usize;
-                            *pos = adjusted_pos + text.len();
-                            *anchor = None;
-                        }
-                        Event::Delete {
-                            range,
-                            cursor_id: event_cursor,
-                            ..
-                        } if event_cursor == cursor_id => {
-                            // For delete, cursor moves to start of deleted range
-                            // Account for shifts from edits at lower positions
-                            let shift = calc_shift(range.start);
-                            *pos = (range.start as isize + shift) as usize;
-                            *anchor = None;
-                        }
-                        _ => {}
-                    }
-                }
-            }
-        }
-
-        // Update cursors in state
-        for (cursor_id, position, anchor) in &new_cursors {
-            if let Some(cursor) = state.cursors.get_mut(*cursor_id) {
-                cursor.position = *position;
-                cursor.anchor = *anchor;
-            }
-        }
-
-        // Invalidate highlighter
-        state.highlighter.invalidate_all();
+        let new_cursors: Vec<(CursorId, usize, Option<usize>)> = state
+            .cursors
+            .iter()
+            .map(|(id, c)| (id, c.position, c.anchor))
+            .collect();
 
         // Create BulkEdit event with both tree snapshots
         let bulk_edit = Event::BulkEdit {
@@ -1827,7 +2346,8 @@ impl Editor {
         self.sync_editor_state_to_split_view_state();
         self.invalidate_layouts_for_buffer(self.active_buffer());
         self.adjust_other_split_cursors_for_event(&bulk_edit);
-        self.clear_search_highlights();
+        self.clear_searches_in_buffer(self.active_buffer());
+        self.sync_lsp_changes(self.active_buffer(), lsp_changes);
 
         Some(bulk_edit)
     }
@@ -1968,12 +2488,7 @@ impl Editor {
 
         // Fire the hook to TypeScript plugins
         if let Some((hook_name, args)) = hook_args {
-            // Update the full plugin state snapshot BEFORE firing the hook
-            // This ensures the plugin can read up-to-date state (diff, cursors, viewport, etc.)
-            // Without this, there's a race condition where the async hook might read stale data
-            #[cfg(feature = "plugins")]
-            self.update_plugin_state_snapshot();
-
+            // Handlers read the state snapshot refreshed when the hook is dispatched
             self.plugin_manager.run_hook(hook_name, args);
         }
     }
@@ -2238,6 +2753,8 @@ impl Editor {
             new_working_dir.display()
         );
         self.restart_with_dir = Some(new_working_dir);
+        // Plugin path watches refer to the old directory
+        self.path_watches.clear();
         // Also signal quit so the event loop exits
         self.should_quit = true;
     }
@@ -2247,6 +2764,16 @@ impl Editor {
         &self.theme
     }
 
+    /// Load a theme by name with the config's overrides applied
+    pub(crate) fn load_theme(&self, name: &str) -> crate::view::theme::Theme {
+        let mut theme =
+            crate::view::theme::Theme::from_name_in(name, Some(&self.dir_context.themes_dir()));
+        if self.config.ui.transparent_background {
+            theme.make_background_transparent();
+        }
+        theme
+    }
+
     /// Check if the settings dialog is open and visible
     pub fn is_settings_open(&self) -> bool {
         self.settings_state.as_ref().is_some_and(|s| s.visible)
@@ -2254,6 +2781,12 @@ impl Editor {
 
     /// Request the editor to quit
     pub fn quit(&mut self) {
+        // Saves still being written finish first; quitting continues after
+        if !self.file_saves.is_empty() {
+            self.wait_for_saves_then_quit();
+            return;
+        }
+
         // Check for unsaved buffers
         let modified_count = self.count_modified_buffers();
         if modified_count > 0 {
@@ -2303,6 +2836,57 @@ impl Editor {
 
         // Resize visible terminal PTYs to match new dimensions
         self.resize_visible_terminals();
+
+        // Hover and signature help point at text that may have moved; other
+        // popups are placed anew, within the new size, on the next render
+        self.dismiss_transient_popups();
+
+        self.plugin_manager.run_hook(
+            "terminal_resized",
+            crate::services::plugins::hooks::HookArgs::TerminalResized { width, height },
+        );
+    }
+
+    /// Resize if the polled terminal size differs from the cached one
+    ///
+    /// Fallback for environments that never deliver resize events (Windows conhost
+    /// in some modes, tmux control mode). Returns true if a resize was applied.
+    pub fn check_terminal_size(&mut self, width: u16, height: u16) -> bool {
+        if (width, height) == (self.terminal_width, self.terminal_height) {
+            return false;
+        }
+        tracing::debug!(
+            "Terminal size changed without a resize event: {}x{} -> {}x{}",
+            self.terminal_width,
+            self.terminal_height,
+            width,
+            height
+        );
+        self.resize(width, height);
+        true
+    }
+
+    /// Current terminal size and capabilities, as reported to plugins
+    pub fn terminal_info(&self) -> crate::services::plugins::api::TerminalInfo {
+        crate::services::plugins::api::TerminalInfo {
+            width: self.terminal_width,
+            height: self.terminal_height,
+            true_color: self.color_capability
+                == crate::view::color_support::ColorCapability::TrueColor,
+            unicode_width_ambiguous: self.config.editor.ambiguous_width.as_str().to_string(),
+        }
+    }
+
+    /// Apply the configured ambiguous character width to layout calculations
+    pub(crate) fn apply_ambiguous_width(&self) {
+        crate::primitives::display_width::set_ambiguous_wide(
+            self.config.editor.ambiguous_width == crate::config::AmbiguousWidth::Wide,
+        );
+    }
+
+    /// Select the glyph table for `ui.ascii_only` and the active theme
+    pub(crate) fn apply_glyphs(&self) {
+        crate::view::glyphs::configure(self.config.ui.ascii_only, &self.theme.glyphs);
     }
 
     // Prompt/Minibuffer control methods
@@ -2324,6 +2908,7 @@ impl Editor {
     ) {
         // Reset any previously stored selection range
         self.pending_search_range = None;
+        self.search_case_override = None;
 
         let selection_range = {
             let state = self.active_state();
@@ -2391,6 +2976,7 @@ impl Editor {
         let needs_suggestions = matches!(
             prompt_type,
             PromptType::OpenFile
+                | PromptType::InsertFile
                 | PromptType::SwitchProject
                 | PromptType::SaveFileAs
                 | PromptType::Command
@@ -2438,7 +3024,7 @@ impl Editor {
                 // Also cancel interactive replace if active
                 self.interactive_replace_state = None;
                 // Clear search highlights from current buffer
-                let ns = self.search_namespace.clone();
+                let ns = search_namespace(self.split_manager.active_split());
                 let state = self.active_state_mut();
                 state.overlays.clear_namespace(&ns, &mut state.marker_list);
             }
@@ -2509,19 +3095,47 @@ impl Editor {
     /// This requests a full editor restart with the new working directory.
     /// The main loop will drop the current editor instance and create a fresh
     /// one pointing to the new directory. This ensures:
-    /// - All buffers are cleanly closed
+    /// - All file buffers are cleanly closed
     /// - LSP servers are properly shut down and restarted with new root
     /// - Plugins are cleanly restarted
-    /// - No state leaks between projects
+    /// - No project state leaks between projects
+    ///
+    /// Untitled buffers, the clipboard, macros and search histories carry
+    /// over to the new instance, see [`ProjectCarryOver`].
     pub fn change_working_dir(&mut self, new_path: PathBuf) {
         // Canonicalize the path to resolve symlinks and normalize
         let new_path = new_path.canonicalize().unwrap_or(new_path);
+        self.record_recent_project_dir(&new_path);
 
         // Request a restart with the new working directory
         // The main loop will handle creating a fresh editor instance
         self.request_restart(new_path);
     }
 
+    /// Resolve a directory to switch to, relative to the working directory
+    ///
+    /// Shows an error and returns None unless the path is an existing directory.
+    pub(crate) fn resolve_project_dir(&mut self, path: &Path) -> Option<PathBuf> {
+        let resolved_path = if path.is_absolute() {
+            normalize_path(path)
+        } else {
+            normalize_path(&self.working_dir.join(path))
+        };
+
+        if resolved_path.is_dir() {
+            Some(resolved_path)
+        } else {
+            self.set_status_message(
+                t!(
+                    "file.not_directory",
+                    path = resolved_path.display().to_string()
+                )
+                .to_string(),
+            );
+            None
+        }
+    }
+
     /// Load directory contents for the file open dialog
     fn load_file_open_directory(&mut self, path: PathBuf) {
         // Update state to loading
@@ -2583,6 +3197,28 @@ impl Editor {
 
     /// Cancel the current prompt and return to normal mode
     pub fn cancel_prompt(&mut self) {
+        // Declining to reload a changed file keeps the buffer as it is
+        if let Some(PromptType::ConfirmReloadChanged { buffer_id }) =
+            self.prompt.as_ref().map(|p| p.prompt_type.clone())
+        {
+            self.keep_changed_buffer(buffer_id);
+        }
+
+        // Declining to create a missing file stops asking about the others
+        if matches!(
+            self.prompt.as_ref().map(|p| &p.prompt_type),
+            Some(PromptType::ConfirmCreateFile { .. })
+        ) {
+            self.cancel_missing_file_prompts();
+        }
+
+        // Declining "Revert Buffer to Saved" closes its diff preview
+        if let Some(PromptType::ConfirmRevertToSaved { buffer_id, preview }) =
+            self.prompt.as_ref().map(|p| p.prompt_type.clone())
+        {
+            self.close_revert_preview(buffer_id, preview);
+        }
+
         // Determine prompt type and reset appropriate history navigation
         if let Some(ref prompt) = self.prompt {
             match &prompt.prompt_type {
@@ -2611,7 +3247,7 @@ impl Editor {
                     };
                     self.apply_event_to_active_buffer(&remove_overlay_event);
                 }
-                PromptType::OpenFile | PromptType::SwitchProject => {
+                PromptType::OpenFile | PromptType::InsertFile | PromptType::SwitchProject => {
                     // Clear file browser state
                     self.file_open_state = None;
                     self.file_browser_layout = None;
@@ -2637,18 +3273,28 @@ impl Editor {
                 prompt.prompt_type,
                 PromptType::Command
                     | PromptType::OpenFile
+                    | PromptType::InsertFile
                     | PromptType::SwitchProject
                     | PromptType::SaveFileAs
                     | PromptType::StopLspServer
                     | PromptType::SelectTheme
                     | PromptType::SelectLocale
                     | PromptType::SwitchToTab
+                    | PromptType::CompareBufferWith
+                    | PromptType::PasteFromHistory
+                    | PromptType::RestoreSessionSnapshot
+                    | PromptType::SwitchLayout
+                    | PromptType::DeleteLayout
+                    | PromptType::OpenRecentProject
             ) {
                 // Use the selected suggestion if any
                 if let Some(selected_idx) = prompt.selected_suggestion {
                     if let Some(suggestion) = prompt.suggestions.get(selected_idx) {
-                        // Don't confirm disabled commands
-                        if suggestion.disabled {
+                        // Don't confirm disabled commands; missing recent
+                        // projects are picked to be removed
+                        if suggestion.disabled
+                            && prompt.prompt_type != PromptType::OpenRecentProject
+                        {
                             self.set_status_message(
                                 t!(
                                     "error.command_not_available",
@@ -2775,6 +3421,13 @@ impl Editor {
         };
 
         match prompt_type {
+            PromptType::Command if input.starts_with(':') => {
+                // `:Name value…` runs the command typed, not a suggestion
+                if let Some(prompt) = &mut self.prompt {
+                    prompt.suggestions.clear();
+                    prompt.selected_suggestion = None;
+                }
+            }
             PromptType::Command => {
                 let selection_active = self.has_active_selection();
                 if let Some(prompt) = &mut self.prompt {
@@ -2803,10 +3456,19 @@ impl Editor {
                 // Reset history navigation when user types - allows Up to navigate history
                 self.replace_history.reset_navigation();
             }
-            PromptType::OpenFile | PromptType::SwitchProject => {
-                // For OpenFile/SwitchProject, update the file browser filter (native implementation)
+            PromptType::OpenFile | PromptType::InsertFile | PromptType::SwitchProject => {
+                // For OpenFile/InsertFile/SwitchProject, update the file browser filter (native implementation)
                 self.update_file_open_filter();
             }
+            PromptType::CommandArgument { command, index, .. } => {
+                self.update_command_arg_suggestions(&command, index, &input);
+            }
+            PromptType::BatchCodeActionKind | PromptType::BatchCodeActionScope { .. } => {
+                self.update_batch_code_action_suggestions(&input);
+            }
+            PromptType::KeyboardShortcutsFilter => {
+                self.filter_keyboard_shortcuts(&input);
+            }
             PromptType::SaveFileAs => {
                 // Fire plugin hook for file path completion.
                 // The hook is processed asynchronously by the plugin thread.
@@ -2833,6 +3495,12 @@ impl Editor {
                 );
             }
             PromptType::SwitchToTab
+            | PromptType::CompareBufferWith
+            | PromptType::PasteFromHistory
+            | PromptType::RestoreSessionSnapshot
+            | PromptType::SwitchLayout
+            | PromptType::DeleteLayout
+            | PromptType::OpenRecentProject
             | PromptType::SelectTheme
             | PromptType::SelectLocale
             | PromptType::StopLspServer => {
@@ -2889,18 +3557,47 @@ impl Editor {
     /// - LSP initialization/errors
     /// - File system changes (future)
     /// - Git status updates (future)
+    ///
+    /// Messages are handled by source priority within a count and time
+    /// budget; those left over stay queued for the next call (see
+    /// [`crate::services::async_queue`]).
     pub fn process_async_messages(&mut self) -> bool {
         let Some(bridge) = &self.async_bridge else {
             return false;
         };
+        for message in bridge.try_recv_all() {
+            self.async_queue.push(message);
+        }
 
-        let messages = bridge.try_recv_all();
-        let needs_render = !messages.is_empty();
-
-        for message in messages {
+        let needs_render = !self.async_queue.is_empty();
+        let started = std::time::Instant::now();
+        let mut handled = 0;
+        while handled < async_queue::MESSAGE_COUNT_BUDGET
+            && started.elapsed() < async_queue::MESSAGE_TIME_BUDGET
+        {
+            let Some(message) = self.async_queue.pop() else {
+                break;
+            };
+            handled += 1;
             match message {
-                AsyncMessage::LspDiagnostics { uri, diagnostics } => {
-                    self.handle_lsp_diagnostics(uri, diagnostics);
+                AsyncMessage::LspDiagnostics {
+                    language,
+                    uri,
+                    diagnostics,
+                } => {
+                    self.handle_lsp_diagnostics(language, uri, diagnostics);
+                }
+                AsyncMessage::LspCompletionTriggers {
+                    language,
+                    characters,
+                } => {
+                    self.set_server_completion_triggers(language, characters);
+                }
+                AsyncMessage::LspCodeActionKinds { language, kinds } => {
+                    self.lsp_code_action_kinds.insert(language, kinds);
+                }
+                AsyncMessage::LspServerSync { language, sync } => {
+                    self.set_lsp_server_sync(language, sync);
                 }
                 AsyncMessage::LspInitialized { language } => {
                     tracing::info!("LSP server initialized for language: {}", language);
@@ -2917,11 +3614,16 @@ impl Editor {
                     tracing::error!("LSP error for {}: {}", language, error);
                     self.status_message = Some(format!("LSP error ({}): {}", language, error));
 
-                    // Get server command from config for the hook
+                    // Get the failed server's command from config for the hook
+                    let server = label_server(&language).map(str::to_string);
                     let server_command = self
                         .config
                         .lsp
-                        .get(&language)
+                        .get(label_language(&language))
+                        .and_then(|config| match &server {
+                            Some(name) => config.server(name),
+                            None => config.primary(),
+                        })
                         .map(|c| c.command.clone())
                         .unwrap_or_else(|| "unknown".to_string());
 
@@ -2941,7 +3643,8 @@ impl Editor {
                     self.plugin_manager.run_hook(
                         "lsp_server_error",
                         crate::services::plugins::hooks::HookArgs::LspServerError {
-                            language: language.clone(),
+                            language: label_language(&language).to_string(),
+                            server,
                             server_command,
                             error_type,
                             message: error.clone(),
@@ -2977,38 +3680,51 @@ impl Editor {
                     }
                 }
                 AsyncMessage::LspCompletion { request_id, items } => {
-                    if let Err(e) = self.handle_completion_response(request_id, items) {
-                        tracing::error!("Error handling completion response: {}", e);
-                    }
+                    self.handle_lsp_answer(request_id, LspAnswer::Completion(items));
                 }
                 AsyncMessage::LspGotoDefinition {
                     request_id,
                     locations,
                 } => {
-                    if let Err(e) = self.handle_goto_definition_response(request_id, locations) {
-                        tracing::error!("Error handling goto definition response: {}", e);
-                    }
+                    self.handle_lsp_answer(request_id, LspAnswer::Definition(locations));
+                }
+                AsyncMessage::LspPrepareRename {
+                    request_id,
+                    supported,
+                    result,
+                } => {
+                    self.handle_prepare_rename_response(request_id, supported, result);
                 }
                 AsyncMessage::LspRename { request_id, result } => {
                     if let Err(e) = self.handle_rename_response(request_id, result) {
                         tracing::error!("Error handling rename response: {}", e);
                     }
                 }
+                AsyncMessage::LspOnTypeFormatting { request_id, edits } => {
+                    if let Err(e) = self.handle_on_type_formatting_response(request_id, edits) {
+                        tracing::error!("Error handling on-type formatting response: {}", e);
+                    }
+                }
                 AsyncMessage::LspHover {
                     request_id,
                     contents,
                     is_markdown,
                     range,
                 } => {
-                    self.handle_hover_response(request_id, contents, is_markdown, range);
+                    self.handle_lsp_answer(
+                        request_id,
+                        LspAnswer::Hover {
+                            contents,
+                            is_markdown,
+                            range,
+                        },
+                    );
                 }
                 AsyncMessage::LspReferences {
                     request_id,
                     locations,
                 } => {
-                    if let Err(e) = self.handle_references_response(request_id, locations) {
-                        tracing::error!("Error handling references response: {}", e);
-                    }
+                    self.handle_lsp_answer(request_id, LspAnswer::References(locations));
                 }
                 AsyncMessage::LspSignatureHelp {
                     request_id,
@@ -3020,16 +3736,23 @@ impl Editor {
                     request_id,
                     actions,
                 } => {
-                    self.handle_code_actions_response(request_id, actions);
+                    self.handle_lsp_answer(request_id, LspAnswer::CodeActions(actions));
                 }
                 AsyncMessage::LspPulledDiagnostics {
+                    language,
                     request_id: _,
                     uri,
                     result_id,
                     diagnostics,
                     unchanged,
                 } => {
-                    self.handle_lsp_pulled_diagnostics(uri, result_id, diagnostics, unchanged);
+                    self.handle_lsp_pulled_diagnostics(
+                        language,
+                        uri,
+                        result_id,
+                        diagnostics,
+                        unchanged,
+                    );
                 }
                 AsyncMessage::LspInlayHints {
                     request_id,
@@ -3044,6 +3767,18 @@ impl Editor {
                 AsyncMessage::FileChanged { path } => {
                     self.handle_async_file_changed(path);
                 }
+                AsyncMessage::FileLoadProgress { path } => {
+                    // Picked up by poll_file_loads below
+                    tracing::trace!("File load progress: {}", path.display());
+                }
+                AsyncMessage::FileSaveProgress { path } => {
+                    // Picked up by poll_file_saves below
+                    tracing::trace!("File save progress: {}", path.display());
+                }
+                AsyncMessage::FormatFinished { path } => {
+                    // Picked up by poll_formats below
+                    tracing::trace!("Formatter finished: {}", path.display());
+                }
                 AsyncMessage::GitStatusChanged { status } => {
                     tracing::info!("Git status changed: {}", status);
                     // TODO: Handle git status changes
@@ -3060,6 +3795,42 @@ impl Editor {
                 AsyncMessage::FileExplorerExpandedToPath(view) => {
                     self.handle_file_explorer_expanded_to_path(view);
                 }
+                AsyncMessage::FileExplorerDirProgress { path } => {
+                    // Picked up by poll_dir_loads below
+                    tracing::trace!("Directory listing progress: {}", path.display());
+                }
+                AsyncMessage::FileExplorerEntryCount {
+                    path,
+                    count,
+                    modified,
+                } => {
+                    self.handle_file_explorer_entry_count(path, count, modified);
+                }
+                AsyncMessage::FileExplorerEntryMetadata { entries } => {
+                    self.handle_file_explorer_entry_metadata(entries);
+                }
+                AsyncMessage::RemoteDirListed { listing_id, result } => {
+                    self.handle_remote_dir_listed(listing_id, result);
+                }
+                AsyncMessage::TodoScanFinished { scan_id, items } => {
+                    self.handle_todo_scan_finished(scan_id, items);
+                }
+                AsyncMessage::DirCompareFinished { compare_id, result } => {
+                    self.handle_dir_compare_finished(compare_id, result);
+                }
+                AsyncMessage::GitBlameFinished {
+                    request_id,
+                    path,
+                    result,
+                } => {
+                    self.handle_git_blame_finished(request_id, path, result);
+                }
+                AsyncMessage::GitCommitShown { hash, result } => {
+                    self.handle_git_commit_shown(hash, result);
+                }
+                AsyncMessage::CommandOutputFinished { request_id, result } => {
+                    self.handle_command_output_finished(request_id, result);
+                }
                 AsyncMessage::PluginProcessOutput {
                     process_id,
                     stdout,
@@ -3162,16 +3933,20 @@ impl Editor {
                     self.terminal_manager.close(terminal_id);
                 }
             }
+            self.validate_if_enabled("async message");
         }
 
-        // Update plugin state snapshot BEFORE processing commands
-        // This ensures plugins have access to current editor state (cursor positions, etc.)
-        #[cfg(feature = "plugins")]
-        self.update_plugin_state_snapshot();
+        // Report file explorer navigation since the last loop; the hook goes out
+        // with the snapshot refreshed at the end of the tick, so handlers read
+        // the same selection from getFileExplorerState()
+        self.check_file_explorer_selection();
 
         // Process TypeScript plugin commands
         let processed_any_commands = self.process_plugin_commands();
 
+        // Take in the next chunk of large virtual buffer content
+        let virtual_content = self.ingest_virtual_content();
+
         // Process pending plugin action completions
         #[cfg(feature = "plugins")]
         self.process_pending_plugin_actions();
@@ -3198,9 +3973,84 @@ impl Editor {
         // Poll for file changes (auto-revert) and file tree changes
         let file_changes = self.poll_file_changes();
         let tree_changes = self.poll_file_tree_changes();
+        let explorer_batch = self.flush_file_explorer_changes();
+        let path_watch_changes = self.poll_path_watches();
+        let chunk_reads = self.poll_buffer_chunk_reads();
+        let theme_reloaded = self.poll_theme_file();
+        let editor_log_changes = self.poll_editor_log();
+
+        // Finish background file loads that have completed
+        let file_loads = self.poll_file_loads();
+
+        // Finish background file saves that have completed
+        let file_saves = self.poll_file_saves();
+
+        // Apply formatters that have finished
+        let formats = self.poll_formats();
+
+        // Continue save actions whose step finished or timed out
+        let save_actions = self.poll_save_actions();
+
+        // Start more files of a batch code action, or stop it
+        let batch_code_action = self.poll_batch_code_action();
+
+        // Show the progress of a self-update
+        let self_update = self.poll_self_update();
+
+        // Recompute the hunks of compared buffers that were edited
+        let buffer_comparison = self.poll_buffer_comparison();
+
+        // Show how far a directory comparison got
+        let dir_compare = self.poll_dir_compare_progress();
+
+        // Explain restored panels that their plugin never filled
+        let panel_timeouts = self.check_panel_restore_timeouts();
+
+        // Finish stale progress entries and turn the spinner
+        let progress = self.check_progress_timers();
+
+        // Send held mouse moves to plugins and close an expired anchored popup
+        let anchored_popup = self.check_anchored_popup();
+
+        // Add entries of directories being listed in the file explorer
+        let dir_loads = self.poll_dir_loads();
+        self.request_entry_counts();
+        self.request_entry_metadata();
+
+        // Shrink the caches once they exceed their memory budget
+        self.check_cache_budget();
+
+        // Compact the undo history of buffers left alone for long
+        self.check_auto_compaction();
+
+        // Last, so the hooks emitted above and by the input handled since the
+        // previous tick see the commands processed above
+        self.dispatch_plugin_hooks();
 
         // Trigger render if any async messages, plugin commands were processed, or plugin requested render
-        needs_render || processed_any_commands || plugin_render || file_changes || tree_changes
+        needs_render
+            || processed_any_commands
+            || virtual_content
+            || plugin_render
+            || file_changes
+            || tree_changes
+            || explorer_batch
+            || path_watch_changes
+            || editor_log_changes
+            || chunk_reads
+            || theme_reloaded
+            || file_loads
+            || file_saves
+            || formats
+            || save_actions
+            || batch_code_action
+            || self_update
+            || buffer_comparison
+            || dir_compare
+            || dir_loads
+            || panel_timeouts
+            || progress
+            || anchored_popup
     }
 
     /// Update LSP status bar string from active progress operations
@@ -3251,6 +4101,7 @@ impl Editor {
                     LspServerStatus::Starting => "starting",
                     LspServerStatus::Initializing => "initializing",
                     LspServerStatus::Running => "ready",
+                    LspServerStatus::Unresponsive => "unresponsive",
                     LspServerStatus::Error => "error",
                     LspServerStatus::Shutdown => "shutdown",
                 };
@@ -3261,12 +4112,28 @@ impl Editor {
         self.lsp_status = format!("LSP [{}]", status_parts.join(", "));
     }
 
+    /// Send the plugin hooks emitted since the last dispatch, with the state
+    /// snapshot refreshed first
+    ///
+    /// The plugin phases of a tick are: input (hooks are queued), plugin
+    /// commands, snapshot refresh, hooks, render (its hooks wait for the next
+    /// tick). The hooks stay queued while the plugins still run the previous
+    /// batch, so each batch's handlers see the effects of the commands that
+    /// the handlers of the batch before sent.
+    pub fn dispatch_plugin_hooks(&mut self) {
+        self.flush_split_changes(false);
+        #[cfg(feature = "plugins")]
+        self.update_plugin_state_snapshot();
+        let limits = self.plugin_hook_loop_limits();
+        self.plugin_manager.dispatch_hooks(limits);
+    }
+
     /// Update the plugin state snapshot with current editor state
     #[cfg(feature = "plugins")]
     fn update_plugin_state_snapshot(&mut self) {
         // Update TypeScript plugin manager state
         if let Some(snapshot_handle) = self.plugin_manager.state_snapshot_handle() {
-            use crate::services::plugins::api::{BufferInfo, CursorInfo, ViewportInfo};
+            use crate::services::plugins::api::{BufferInfo, CursorInfo, PromptInfo, ViewportInfo};
             let mut snapshot = snapshot_handle.write().unwrap();
 
             // Update active buffer ID
@@ -3287,6 +4154,11 @@ impl Editor {
                     path: state.buffer.file_path().map(|p| p.to_path_buf()),
                     modified: state.buffer.is_modified(),
                     length: state.buffer.len(),
+                    read_only_reason: self
+                        .buffer_metadata
+                        .get(buffer_id)
+                        .and_then(|metadata| metadata.fs_read_only),
+                    pinned: self.is_buffer_pinned(*buffer_id),
                 };
                 snapshot.buffers.insert(*buffer_id, buffer_info);
 
@@ -3315,11 +4187,13 @@ impl Editor {
                     .buffer_cursor_positions
                     .insert(*buffer_id, cursor_pos);
 
-                // Store text properties if this buffer has any
-                if !state.text_properties.is_empty() {
+                // Store the text properties at the cursor, not all of them:
+                // a panel can have tens of thousands
+                let properties = state.text_properties.get_at(cursor_pos);
+                if !properties.is_empty() {
                     snapshot
                         .buffer_text_properties
-                        .insert(*buffer_id, state.text_properties.all().to_vec());
+                        .insert(*buffer_id, properties.into_iter().cloned().collect());
                 }
             }
 
@@ -3334,6 +4208,8 @@ impl Editor {
                     position: primary_position,
                     selection: primary_selection.clone(),
                 });
+                snapshot.primary_cursor_line =
+                    Some(active_state.buffer.get_line_number(primary_position));
 
                 // Selected text from primary cursor (for clipboard plugin)
                 snapshot.selected_text = if let Some(range) = primary_selection {
@@ -3366,6 +4242,7 @@ impl Editor {
                 }
             } else {
                 snapshot.primary_cursor = None;
+                snapshot.primary_cursor_line = None;
                 snapshot.all_cursors.clear();
                 snapshot.viewport = None;
                 snapshot.selected_text = None;
@@ -3378,22 +4255,92 @@ impl Editor {
             snapshot.working_dir = self.working_dir.clone();
 
             // Update LSP diagnostics
-            snapshot.diagnostics = self.stored_diagnostics.clone();
+            snapshot.diagnostics = self.stored_diagnostics.by_uri();
+            snapshot.diagnostics_summary = self.stored_diagnostics.summary();
 
             // Update config (serialize the runtime config for plugins)
             snapshot.config = serde_json::to_value(&self.config).unwrap_or(serde_json::Value::Null);
 
             // Update user config (raw file contents, not merged with defaults)
             // This allows plugins to distinguish between user-set and default values
-            snapshot.user_config = Config::read_user_config_raw(&self.working_dir);
+            snapshot.user_config =
+                Config::read_user_config_raw(&self.dir_context, &self.working_dir);
+
+            // Update theme colors, resolved for plugins that need numbers
+            snapshot.theme_colors = self
+                .theme
+                .named_colors()
+                .into_iter()
+                .filter_map(|(name, color)| Some((name.to_string(), color_to_rgb(color)?)))
+                .collect();
 
             // Update editor mode (for vi mode and other modal editing)
             snapshot.editor_mode = self.editor_mode.clone();
+
+            // Update recent projects
+            snapshot.recent_projects = self
+                .recent_projects
+                .ordered()
+                .into_iter()
+                .cloned()
+                .collect();
+
+            // Update split input locks
+            snapshot.locked_splits = self
+                .split_view_states
+                .iter()
+                .filter(|(_, view_state)| view_state.input_locked)
+                .map(|(split_id, _)| split_id.0)
+                .collect();
+            snapshot.locked_splits.sort_unstable();
+
+            // Update terminal size and capabilities
+            snapshot.terminal = self.terminal_info();
+
+            // Update file explorer state (kept as-is while a reveal has the tree)
+            if !self.file_explorer_sync_in_progress {
+                snapshot.file_explorer = self.file_explorer_info();
+            }
+
+            // Update active prompt state
+            snapshot.prompt = self.prompt.as_ref().map(|prompt| PromptInfo {
+                prompt_type: match &prompt.prompt_type {
+                    PromptType::Plugin { custom_type } => Some(custom_type.clone()),
+                    _ => None,
+                },
+                label: prompt.message.clone(),
+                text: prompt.input.clone(),
+                cursor: prompt.cursor_pos,
+                selected_suggestion: prompt.selected_suggestion,
+            });
         }
     }
 
     /// Handle a plugin command - dispatches to specialized handlers in plugin_commands module
+    ///
+    /// Display text the command carries is sanitized first, see
+    /// [`PluginCommand::sanitize_display_text`]. Split changes the command
+    /// makes fire their hooks with `by_plugin` set.
     pub fn handle_plugin_command(&mut self, command: PluginCommand) -> io::Result<()> {
+        self.flush_split_changes(false);
+        let result = self.run_plugin_command(command);
+        self.flush_split_changes(true);
+        result
+    }
+
+    fn run_plugin_command(&mut self, mut command: PluginCommand) -> io::Result<()> {
+        command.sanitize_display_text(crate::view::glyphs::glyphs().ellipsis);
+        if let Some((_, PluginCommandTarget::Buffer(buffer_id))) = command.target() {
+            self.touch_plugin_buffer(buffer_id);
+            // The plugin works on all of the content it sent
+            if !matches!(
+                command,
+                PluginCommand::SetVirtualBufferContent { .. }
+                    | PluginCommand::AppendVirtualBufferContent { .. }
+            ) {
+                self.flush_virtual_content(buffer_id);
+            }
+        }
         match command {
             // ==================== Text Editing Commands ====================
             PluginCommand::InsertText {
@@ -3406,6 +4353,16 @@ impl Editor {
             PluginCommand::DeleteRange { buffer_id, range } => {
                 self.handle_delete_range(buffer_id, range);
             }
+            PluginCommand::ApplyEdits {
+                buffer_id,
+                edits,
+                label,
+            } => {
+                self.handle_apply_edits(buffer_id, edits, label);
+            }
+            PluginCommand::FinishSaveAction { request_id, error } => {
+                self.finish_plugin_save_action(request_id, error);
+            }
             PluginCommand::InsertAtCursor { text } => {
                 self.handle_insert_at_cursor(text);
             }
@@ -3557,6 +4514,26 @@ impl Editor {
             PluginCommand::SetSplitRatio { split_id, ratio } => {
                 self.handle_set_split_ratio(split_id, ratio);
             }
+            PluginCommand::SetSplitLocked { split_id, locked } => {
+                self.set_split_locked(split_id, locked);
+            }
+            PluginCommand::SetWinbarSegment {
+                split_id,
+                id,
+                text,
+                color,
+                priority,
+            } => {
+                self.handle_set_winbar_segment(
+                    split_id,
+                    crate::view::ui::winbar::WinbarSegment {
+                        id,
+                        text,
+                        color,
+                        priority,
+                    },
+                );
+            }
             PluginCommand::DistributeSplitsEvenly { split_ids: _ } => {
                 self.handle_distribute_splits_evenly();
             }
@@ -3566,6 +4543,9 @@ impl Editor {
             } => {
                 self.handle_set_buffer_cursor(buffer_id, position);
             }
+            PluginCommand::GotoByte { buffer_id, offset } => {
+                self.handle_goto_byte_command(buffer_id, offset);
+            }
 
             // ==================== View/Layout Commands ====================
             PluginCommand::SetLayoutHints {
@@ -3579,6 +4559,14 @@ impl Editor {
             PluginCommand::SetLineNumbers { buffer_id, enabled } => {
                 self.handle_set_line_numbers(buffer_id, enabled);
             }
+            PluginCommand::SetBufferIdleClose { buffer_id, enabled } => {
+                self.set_plugin_buffer_idle_close(buffer_id, enabled);
+            }
+            PluginCommand::SetBufferPinned { buffer_id, pinned } => {
+                if !self.set_buffer_pinned(buffer_id, pinned) {
+                    tracing::warn!("setBufferPinned: buffer {:?} not found", buffer_id);
+                }
+            }
             PluginCommand::SubmitViewTransform {
                 buffer_id,
                 split_id,
@@ -3602,8 +4590,11 @@ impl Editor {
                 symbol,
                 color,
                 priority,
+                column,
             } => {
-                self.handle_set_line_indicator(buffer_id, line, namespace, symbol, color, priority);
+                self.handle_set_line_indicator(
+                    buffer_id, line, namespace, symbol, color, priority, column,
+                );
             }
             PluginCommand::ClearLineIndicators {
                 buffer_id,
@@ -3611,6 +4602,36 @@ impl Editor {
             } => {
                 self.handle_clear_line_indicators(buffer_id, namespace);
             }
+            PluginCommand::RegisterGutterColumn {
+                name,
+                width,
+                alignment,
+            } => {
+                self.handle_register_gutter_column(name, width, alignment);
+            }
+            PluginCommand::UnregisterGutterColumn { name } => {
+                self.handle_unregister_gutter_column(name);
+            }
+            PluginCommand::RegisterExplorerAction {
+                id,
+                label,
+                when,
+                plugin,
+            } => {
+                self.handle_register_explorer_action(id, label, when, plugin);
+            }
+            PluginCommand::UnregisterExplorerAction { id } => {
+                self.handle_unregister_explorer_action(id);
+            }
+            PluginCommand::HookLoopCut {
+                plugin,
+                hook,
+                buffer_id,
+                depth,
+                runs,
+            } => {
+                self.handle_hook_loop_cut(plugin, hook, buffer_id, depth, runs);
+            }
 
             // ==================== Status/Prompt Commands ====================
             PluginCommand::SetStatus { message } => {
@@ -3622,6 +4643,9 @@ impl Editor {
             PluginCommand::ReloadConfig => {
                 self.reload_config();
             }
+            PluginCommand::SetConfigValue { path, value } => {
+                self.set_config_value(&path, value);
+            }
             PluginCommand::StartPrompt { label, prompt_type } => {
                 self.handle_start_prompt(label, prompt_type);
             }
@@ -3635,10 +4659,20 @@ impl Editor {
             PluginCommand::SetPromptSuggestions { suggestions } => {
                 self.handle_set_prompt_suggestions(suggestions);
             }
+            PluginCommand::ClosePrompt { prompt_type } => {
+                self.handle_close_prompt(prompt_type);
+            }
+            PluginCommand::AcceptPromptSuggestion { index } => {
+                return self.handle_accept_prompt_suggestion(index);
+            }
 
             // ==================== Command/Mode Registration ====================
-            PluginCommand::RegisterCommand { command } => {
-                self.handle_register_command(command);
+            PluginCommand::RegisterCommand {
+                command,
+                dry_run,
+                args,
+            } => {
+                self.handle_register_command(command, dry_run, args);
             }
             PluginCommand::UnregisterCommand { name } => {
                 self.handle_unregister_command(name);
@@ -3648,8 +4682,9 @@ impl Editor {
                 parent,
                 bindings,
                 read_only,
+                plugin,
             } => {
-                self.handle_define_mode(name, parent, bindings, read_only);
+                self.handle_define_mode(name, parent, bindings, read_only, plugin);
             }
 
             // ==================== File/Navigation Commands ====================
@@ -3670,6 +4705,14 @@ impl Editor {
             PluginCommand::ShowBuffer { buffer_id } => {
                 self.handle_show_buffer(buffer_id);
             }
+            PluginCommand::CreateBuffer {
+                content,
+                language,
+                name_hint,
+                request_id,
+            } => {
+                self.handle_create_buffer(content, language, name_hint, request_id);
+            }
             PluginCommand::CloseBuffer { buffer_id } => {
                 self.handle_close_buffer(buffer_id);
             }
@@ -3711,6 +4754,7 @@ impl Editor {
                 read_only,
             } => {
                 let buffer_id = self.create_virtual_buffer(name.clone(), mode.clone(), read_only);
+                self.register_plugin_buffer(buffer_id);
                 tracing::info!(
                     "Created virtual buffer '{}' with mode '{}' (id={:?})",
                     name,
@@ -3727,9 +4771,11 @@ impl Editor {
                 show_line_numbers,
                 show_cursors,
                 editing_disabled,
+                parse_ansi,
                 request_id,
             } => {
                 let buffer_id = self.create_virtual_buffer(name.clone(), mode.clone(), read_only);
+                self.register_plugin_buffer(buffer_id);
                 tracing::info!(
                     "Created virtual buffer '{}' with mode '{}' (id={:?})",
                     name,
@@ -3751,8 +4797,10 @@ impl Editor {
                     );
                 }
 
+                self.set_virtual_buffer_parse_ansi(buffer_id, parse_ansi);
+
                 // Now set the content
-                match self.set_virtual_buffer_content(buffer_id, entries) {
+                match self.set_plugin_virtual_buffer_content(buffer_id, entries) {
                     Ok(()) => {
                         tracing::debug!("Set virtual buffer content for {:?}", buffer_id);
                         // Switch to the new buffer to display it
@@ -3791,7 +4839,10 @@ impl Editor {
                 show_line_numbers,
                 show_cursors,
                 editing_disabled,
+                parse_ansi,
                 line_wrap,
+                persistent,
+                plugin,
                 request_id,
             } => {
                 // Check if this panel already exists (for idempotent operations)
@@ -3799,9 +4850,19 @@ impl Editor {
                     if let Some(&existing_buffer_id) = self.panel_ids.get(pid) {
                         // Verify the buffer actually exists (defensive check for stale entries)
                         if self.buffers.contains_key(&existing_buffer_id) {
+                            self.register_persistent_panel(
+                                existing_buffer_id,
+                                persistent,
+                                plugin,
+                                pid,
+                            );
+                            self.touch_plugin_buffer(existing_buffer_id);
+
+                            self.set_virtual_buffer_parse_ansi(existing_buffer_id, parse_ansi);
+
                             // Panel exists, just update its content
                             if let Err(e) =
-                                self.set_virtual_buffer_content(existing_buffer_id, entries)
+                                self.set_plugin_virtual_buffer_content(existing_buffer_id, entries)
                             {
                                 tracing::error!("Failed to update panel content: {}", e);
                             } else {
@@ -3847,6 +4908,7 @@ impl Editor {
 
                 // Create the virtual buffer first
                 let buffer_id = self.create_virtual_buffer(name.clone(), mode.clone(), read_only);
+                self.register_plugin_buffer(buffer_id);
                 tracing::info!(
                     "Created virtual buffer '{}' with mode '{}' in split (id={:?})",
                     name,
@@ -3870,11 +4932,14 @@ impl Editor {
 
                 // Store the panel ID mapping if provided
                 if let Some(pid) = panel_id {
+                    self.register_persistent_panel(buffer_id, persistent, plugin, &pid);
                     self.panel_ids.insert(pid, buffer_id);
                 }
 
+                self.set_virtual_buffer_parse_ansi(buffer_id, parse_ansi);
+
                 // Set the content
-                if let Err(e) = self.set_virtual_buffer_content(buffer_id, entries) {
+                if let Err(e) = self.set_plugin_virtual_buffer_content(buffer_id, entries) {
                     tracing::error!("Failed to set virtual buffer content: {}", e);
                     return Ok(());
                 }
@@ -3934,7 +4999,7 @@ impl Editor {
                 }
             }
             PluginCommand::SetVirtualBufferContent { buffer_id, entries } => {
-                match self.set_virtual_buffer_content(buffer_id, entries) {
+                match self.set_plugin_virtual_buffer_content(buffer_id, entries) {
                     Ok(()) => {
                         tracing::debug!("Set virtual buffer content for {:?}", buffer_id);
                     }
@@ -3943,6 +5008,11 @@ impl Editor {
                     }
                 }
             }
+            PluginCommand::AppendVirtualBufferContent { buffer_id, entries } => {
+                if let Err(e) = self.append_virtual_buffer_content(buffer_id, entries) {
+                    tracing::error!("Failed to append virtual buffer content: {}", e);
+                }
+            }
             PluginCommand::GetTextPropertiesAtCursor { buffer_id } => {
                 // Get text properties at cursor and fire a hook with the data
                 if let Some(state) = self.buffers.get(&buffer_id) {
@@ -3965,11 +5035,13 @@ impl Editor {
                 show_line_numbers,
                 show_cursors,
                 editing_disabled,
+                parse_ansi,
                 line_wrap,
                 request_id,
             } => {
                 // Create the virtual buffer
                 let buffer_id = self.create_virtual_buffer(name.clone(), mode.clone(), read_only);
+                self.register_plugin_buffer(buffer_id);
                 tracing::info!(
                     "Created virtual buffer '{}' with mode '{}' for existing split {:?} (id={:?})",
                     name,
@@ -3985,8 +5057,10 @@ impl Editor {
                     state.editing_disabled = editing_disabled;
                 }
 
+                self.set_virtual_buffer_parse_ansi(buffer_id, parse_ansi);
+
                 // Set the content
-                if let Err(e) = self.set_virtual_buffer_content(buffer_id, entries) {
+                if let Err(e) = self.set_plugin_virtual_buffer_content(buffer_id, entries) {
                     tracing::error!("Failed to set virtual buffer content: {}", e);
                     return Ok(());
                 }
@@ -4051,19 +5125,114 @@ impl Editor {
             PluginCommand::ExecuteActions { actions } => {
                 self.handle_execute_actions(actions);
             }
+            PluginCommand::ExecuteActionWithResult {
+                action_name,
+                request_id,
+            } => {
+                self.handle_execute_action_with_result(action_name, request_id);
+            }
+            PluginCommand::ExecuteActionsWithResult {
+                actions,
+                request_id,
+            } => {
+                self.handle_execute_actions_with_result(actions, request_id);
+            }
+            PluginCommand::GetKeymap {
+                context,
+                request_id,
+            } => {
+                self.handle_get_keymap(context, request_id);
+            }
             PluginCommand::GetBufferText {
                 buffer_id,
                 start,
                 end,
+                plugin,
+                request_id,
+            } => {
+                self.handle_get_buffer_text(buffer_id, start, end, plugin, request_id);
+            }
+            PluginCommand::ByteToLineCol {
+                buffer_id,
+                byte,
+                request_id,
+            } => {
+                self.handle_byte_to_line_col(buffer_id, byte, request_id);
+            }
+            PluginCommand::LineColToByte {
+                buffer_id,
+                line,
+                column,
                 request_id,
             } => {
-                self.handle_get_buffer_text(buffer_id, start, end, request_id);
+                self.handle_line_col_to_byte(buffer_id, line, column, request_id);
+            }
+            PluginCommand::ReadBufferChunks {
+                buffer_id,
+                chunk_size,
+                read_id,
+                plugin,
+            } => {
+                self.start_buffer_chunk_read(read_id, plugin, buffer_id, chunk_size);
             }
             PluginCommand::SetEditorMode { mode } => {
                 self.handle_set_editor_mode(mode);
             }
+            PluginCommand::SetCursorStyle { style } => {
+                self.handle_set_cursor_style(style);
+            }
+            PluginCommand::SetRevertStableNamespace { namespace, stable } => {
+                if stable {
+                    self.revert_stable_namespaces.insert(namespace);
+                } else {
+                    self.revert_stable_namespaces.remove(&namespace);
+                }
+            }
+
+            PluginCommand::Notify {
+                notification_id,
+                message,
+                severity,
+                timeout_ms,
+                actions,
+            } => {
+                let timeout = match timeout_ms {
+                    None => Some(crate::view::notifications::DEFAULT_TIMEOUT),
+                    Some(0) => None,
+                    Some(ms) => Some(std::time::Duration::from_millis(ms)),
+                };
+                let actions = actions
+                    .into_iter()
+                    .map(|a| crate::view::notifications::NotificationAction {
+                        id: a.id,
+                        label: a.label,
+                    })
+                    .collect();
+                self.post_notification(
+                    crate::view::notifications::Notification::new(
+                        notification_id,
+                        severity,
+                        message,
+                    )
+                    .with_actions(actions)
+                    .with_timeout(timeout),
+                );
+            }
 
             // ==================== LSP Helper Commands ====================
+            PluginCommand::ShowAnchoredPopup {
+                buffer_id,
+                position,
+                lines,
+                max_width,
+                timeout_ms,
+            } => {
+                self.show_anchored_popup(buffer_id, position, lines, max_width, timeout_ms);
+            }
+            PluginCommand::CloseAnchoredPopup => {
+                self.close_anchored_popup();
+            }
+
             PluginCommand::ShowActionPopup {
                 popup_id,
                 title,
@@ -4113,31 +5282,105 @@ impl Editor {
                 );
             }
 
-            PluginCommand::DisableLspForLanguage { language } => {
-                tracing::info!("Disabling LSP for language: {}", language);
+            // ==================== Pending Edits Commands ====================
+            PluginCommand::ReviewPendingEdits {
+                title,
+                edits,
+                apply,
+                request_id,
+            } => {
+                tracing::debug!("Reviewing {} pending edits: {}", edits.len(), title);
+                let on_confirm = if apply {
+                    pending_edits::OnConfirm::Apply
+                } else {
+                    pending_edits::OnConfirm::Report
+                };
+                self.start_pending_edits_review(title, edits, Some(request_id), on_confirm);
+            }
+
+            PluginCommand::WatchPath {
+                watch_id,
+                plugin,
+                pattern,
+            } => {
+                self.add_path_watch(watch_id, plugin, &pattern);
+            }
+            PluginCommand::UnwatchPath { watch_id } => {
+                self.remove_path_watch(watch_id);
+            }
+            PluginCommand::StartProgress {
+                progress_id,
+                plugin,
+                title,
+                cancellable,
+            } => {
+                self.begin_progress(
+                    progress_id,
+                    crate::view::progress::ProgressOwner::Plugin(plugin),
+                    title,
+                    cancellable,
+                );
+            }
+            PluginCommand::UpdateProgress {
+                progress_id,
+                percentage,
+                message,
+            } => {
+                self.update_progress(progress_id, percentage, message);
+            }
+            PluginCommand::FinishProgress {
+                progress_id,
+                abandoned,
+            } => {
+                if abandoned {
+                    self.abandon_progress(progress_id);
+                } else {
+                    self.finish_progress(progress_id);
+                }
+            }
+            PluginCommand::RevealInFileExplorer { path } => {
+                self.handle_reveal_in_file_explorer(path);
+            }
+            PluginCommand::SetFileExplorerRoot { path } => {
+                self.set_file_explorer_root(&path);
+            }
+
+            PluginCommand::DisableLspForLanguage { language, server } => {
+                // One of the language's servers, or all of them
+                let target = match &server {
+                    Some(name) => format!("{}/{}", language, name),
+                    None => language.clone(),
+                };
+                tracing::info!("Disabling LSP for: {}", target);
 
                 // 1. Stop the LSP server for this language if running
                 if let Some(ref mut lsp) = self.lsp {
-                    lsp.shutdown_server(&language);
-                    tracing::info!("Stopped LSP server for {}", language);
+                    lsp.shutdown_server(&target);
+                    tracing::info!("Stopped LSP server for {}", target);
                 }
 
                 // 2. Update the config to disable the language
                 if let Some(lsp_config) = self.config.lsp.get_mut(&language) {
-                    lsp_config.enabled = false;
-                    lsp_config.auto_start = false;
-                    tracing::info!("Disabled LSP config for {}", language);
+                    for server_config in lsp_config.servers_mut() {
+                        if server
+                            .as_ref()
+                            .is_some_and(|name| *name != server_config.server_name())
+                        {
+                            continue;
+                        }
+                        server_config.enabled = false;
+                        server_config.auto_start = false;
+                    }
+                    tracing::info!("Disabled LSP config for {}", target);
                 }
 
                 // 3. Persist the config change
                 if let Err(e) = self.save_config() {
                     tracing::error!("Failed to save config: {}", e);
-                    self.status_message = Some(format!(
-                        "LSP disabled for {} (config save failed)",
-                        language
-                    ));
+                    self.status_message =
+                        Some(format!("LSP disabled for {} (config save failed)", target));
                 } else {
-                    self.status_message = Some(format!("LSP disabled for {}", language));
+                    self.status_message = Some(format!("LSP disabled for {}", target));
                 }
 
                 // 4. Clear any LSP-related warnings for this language
@@ -4193,6 +5436,9 @@ impl Editor {
                     tracing::warn!("Scroll sync group {} not found", group_id);
                 }
             }
+            PluginCommand::CompareBuffers { left, right } => {
+                self.compare_buffers(left, right);
+            }
         }
         Ok(())
     }
@@ -4242,34 +5488,87 @@ impl Editor {
         }
     }
 
-    /// Get text from a buffer range (for vi mode yank operations)
-    fn handle_get_buffer_text(
+    /// Execute an action and send its outcome back to the plugin
+    fn handle_execute_action_with_result(&mut self, action_name: String, request_id: u64) {
+        let result = self.execute_action_for_plugin(&action_name, 1);
+        self.send_plugin_response(
+            crate::services::plugins::api::PluginResponse::ActionsExecuted {
+                request_id,
+                results: vec![result],
+            },
+        );
+    }
+
+    /// Execute actions in sequence and send each one's outcome back to the plugin
+    /// Stops at the first failing action, like `handle_execute_actions`
+    fn handle_execute_actions_with_result(
         &mut self,
-        buffer_id: BufferId,
-        start: usize,
-        end: usize,
+        actions: Vec<crate::services::plugins::api::ActionSpec>,
         request_id: u64,
     ) {
-        let result = if let Some(state) = self.buffers.get_mut(&buffer_id) {
-            // Get text from the buffer using the mutable get_text_range method
-            let len = state.buffer.len();
-            if start <= end && end <= len {
-                Ok(state.get_text_range(start, end))
-            } else {
-                Err(format!(
-                    "Invalid range {}..{} for buffer of length {}",
-                    start, end, len
-                ))
+        let mut results = Vec::with_capacity(actions.len());
+        for action_spec in actions {
+            let result = self.execute_action_for_plugin(&action_spec.action, action_spec.count);
+            let ok = result.ok;
+            results.push(result);
+            if !ok {
+                break;
             }
-        } else {
-            Err(format!("Buffer {:?} not found", buffer_id))
+        }
+        self.send_plugin_response(
+            crate::services::plugins::api::PluginResponse::ActionsExecuted {
+                request_id,
+                results,
+            },
+        );
+    }
+
+    /// Execute an action `count` times and report what it did
+    fn execute_action_for_plugin(
+        &mut self,
+        action_name: &str,
+        count: u32,
+    ) -> crate::services::plugins::api::ActionResult {
+        use crate::input::keybindings::Action;
+        use crate::services::plugins::api::ActionResult;
+        use std::collections::HashMap;
+
+        let Some(action) = Action::from_str(action_name, &HashMap::new()) else {
+            tracing::warn!("Unknown action: {}", action_name);
+            return ActionResult {
+                ok: false,
+                error: Some(format!("Unknown action: {}", action_name)),
+                ..Default::default()
+            };
         };
 
-        // Send response via plugin manager
-        self.send_plugin_response(crate::services::plugins::api::PluginResponse::BufferText {
-            request_id,
-            text: result,
+        // Piece tree roots are shared via Arc, so comparing them tells whether
+        // any edit happened without comparing content
+        let buffer_id = self.active_buffer();
+        let cursor_before = self.active_state().cursors.primary().position;
+        let root_before = self.active_state().buffer.snapshot_piece_tree().root();
+
+        let mut error = None;
+        for _ in 0..count {
+            if let Err(e) = self.handle_action(action.clone()) {
+                tracing::warn!("Failed to execute action '{}': {}", action_name, e);
+                error = Some(e.to_string());
+                break;
+            }
+        }
+
+        let buffer_changed = self.buffers.get(&buffer_id).is_some_and(|state| {
+            !Arc::ptr_eq(&root_before, &state.buffer.snapshot_piece_tree().root())
         });
+        let cursor_moved = self.active_buffer() != buffer_id
+            || self.active_state().cursors.primary().position != cursor_before;
+
+        ActionResult {
+            ok: error.is_none(),
+            error,
+            cursor_moved,
+            buffer_changed,
+        }
     }
 
     /// Set the global editor mode (for vi mode)
@@ -4353,8 +5652,18 @@ fn parse_key_string(key_str: &str) -> Option<(KeyCode, KeyModifiers)> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::model::position_encoding::PositionEncoding;
+    use lsp_types::{Position, Range as LspRange, TextDocumentContentChangeEvent};
     use tempfile::TempDir;
 
+    /// The ranges of a change expected to have them
+    fn range_changes_of(changes: DocumentChanges) -> Vec<TextDocumentContentChangeEvent> {
+        match changes {
+            DocumentChanges::Ranges(changes) => changes,
+            DocumentChanges::Whole => panic!("expected range changes, got the whole text"),
+        }
+    }
+
     /// Create a test DirectoryContext with temp directories
     fn test_dir_context() -> (DirectoryContext, TempDir) {
         let temp_dir = TempDir::new().unwrap();
@@ -4950,7 +6259,7 @@ mod tests {
         // Insert "NEW" at position 0 (before "hello")
         // Expected LSP range: line 0, char 0 to line 0, char 0 (zero-width)
         let position = 0;
-        let (line, character) = buffer.position_to_lsp_position(position);
+        let (line, character) = buffer.position_to_lsp_position(position, PositionEncoding::Utf16);
 
         assert_eq!(line, 0, "Insertion at start should be line 0");
         assert_eq!(character, 0, "Insertion at start should be char 0");
@@ -4970,14 +6279,14 @@ mod tests {
 
         // Test insertion at middle of first line (position 3, after "hel")
         let position = 3;
-        let (line, character) = buffer.position_to_lsp_position(position);
+        let (line, character) = buffer.position_to_lsp_position(position, PositionEncoding::Utf16);
 
         assert_eq!(line, 0);
         assert_eq!(character, 3);
 
         // Test insertion at start of second line (position 6, after "hello\n")
         let position = 6;
-        let (line, character) = buffer.position_to_lsp_position(position);
+        let (line, character) = buffer.position_to_lsp_position(position, PositionEncoding::Utf16);
 
         assert_eq!(line, 1, "Position after newline should be line 1");
         assert_eq!(character, 0, "Position at start of line 2 should be char 0");
@@ -4995,8 +6304,10 @@ mod tests {
         let range_start = 1;
         let range_end = 5;
 
-        let (start_line, start_char) = buffer.position_to_lsp_position(range_start);
-        let (end_line, end_char) = buffer.position_to_lsp_position(range_end);
+        let (start_line, start_char) =
+            buffer.position_to_lsp_position(range_start, PositionEncoding::Utf16);
+        let (end_line, end_char) =
+            buffer.position_to_lsp_position(range_end, PositionEncoding::Utf16);
 
         assert_eq!(start_line, 0);
         assert_eq!(start_char, 1);
@@ -5021,8 +6332,10 @@ mod tests {
         let range_start = 4;
         let range_end = 8;
 
-        let (start_line, start_char) = buffer.position_to_lsp_position(range_start);
-        let (end_line, end_char) = buffer.position_to_lsp_position(range_end);
+        let (start_line, start_char) =
+            buffer.position_to_lsp_position(range_start, PositionEncoding::Utf16);
+        let (end_line, end_char) =
+            buffer.position_to_lsp_position(range_end, PositionEncoding::Utf16);
 
         assert_eq!(start_line, 0, "Delete start on line 0");
         assert_eq!(start_char, 4, "Delete start at char 4");
@@ -5040,13 +6353,13 @@ mod tests {
         let buffer = Buffer::from_str_test("😀hello");
 
         // Position 4 is after the emoji (4 bytes)
-        let (line, character) = buffer.position_to_lsp_position(4);
+        let (line, character) = buffer.position_to_lsp_position(4, PositionEncoding::Utf16);
 
         assert_eq!(line, 0);
         assert_eq!(character, 2, "Emoji should count as 2 UTF-16 code units");
 
         // Position 9 is after "😀hell" (4 bytes emoji + 5 bytes text)
-        let (line, character) = buffer.position_to_lsp_position(9);
+        let (line, character) = buffer.position_to_lsp_position(9, PositionEncoding::Utf16);
 
         assert_eq!(line, 0);
         assert_eq!(
@@ -5058,13 +6371,13 @@ mod tests {
         let buffer = Buffer::from_str_test("café");
 
         // Position 3 is after "caf" (3 bytes)
-        let (line, character) = buffer.position_to_lsp_position(3);
+        let (line, character) = buffer.position_to_lsp_position(3, PositionEncoding::Utf16);
 
         assert_eq!(line, 0);
         assert_eq!(character, 3);
 
         // Position 5 is after "café" (3 + 2 bytes)
-        let (line, character) = buffer.position_to_lsp_position(5);
+        let (line, character) = buffer.position_to_lsp_position(5, PositionEncoding::Utf16);
 
         assert_eq!(line, 0);
         assert_eq!(character, 4, "é should count as 1 UTF-16 code unit");
@@ -5253,7 +6566,7 @@ mod tests {
         editor.search_case_sensitive = false;
         editor.perform_search("hello");
 
-        let search_state = editor.search_state.as_ref().unwrap();
+        let search_state = editor.active_search().unwrap();
         assert_eq!(
             search_state.matches.len(),
             3,
@@ -5264,7 +6577,7 @@ mod tests {
         editor.search_case_sensitive = true;
         editor.perform_search("hello");
 
-        let search_state = editor.search_state.as_ref().unwrap();
+        let search_state = editor.active_search().unwrap();
         assert_eq!(
             search_state.matches.len(),
             1,
@@ -5302,7 +6615,7 @@ mod tests {
         editor.search_case_sensitive = true;
         editor.perform_search("test");
 
-        let search_state = editor.search_state.as_ref().unwrap();
+        let search_state = editor.active_search().unwrap();
         assert_eq!(
             search_state.matches.len(),
             5,
@@ -5313,7 +6626,7 @@ mod tests {
         editor.search_whole_word = true;
         editor.perform_search("test");
 
-        let search_state = editor.search_state.as_ref().unwrap();
+        let search_state = editor.active_search().unwrap();
         assert_eq!(
             search_state.matches.len(),
             2,
@@ -5568,14 +6881,14 @@ mod tests {
         };
 
         // CORRECT: Calculate LSP positions BEFORE applying batch
-        let lsp_changes_before = editor.collect_lsp_changes(&batch);
+        let lsp_changes_before = range_changes_of(editor.collect_lsp_changes(&batch));
 
         // Now apply the batch (this is what apply_events_to_buffer_as_bulk_edit does)
         editor.active_state_mut().apply(&batch);
 
         // BUG DEMONSTRATION: Calculate LSP positions AFTER applying batch
         // This is what happens when notify_lsp_change is called after state.apply()
-        let lsp_changes_after = editor.collect_lsp_changes(&batch);
+        let lsp_changes_after = range_changes_of(editor.collect_lsp_changes(&batch));
 
         // Verify buffer was correctly modified
         let final_content = editor.active_state().buffer.to_string().unwrap();
@@ -5721,7 +7034,7 @@ mod tests {
 
         // Apply the rename using bulk edit (this should preserve cursor position)
         editor
-            .apply_events_to_buffer_as_bulk_edit(buffer_id, events, "LSP Rename".to_string())
+            .apply_events_to_buffer_as_bulk_edit(buffer_id, events, "LSP Rename".to_string(), None)
             .unwrap();
 
         // Verify buffer was correctly modified
@@ -5814,7 +7127,7 @@ mod tests {
         };
 
         // Collect LSP changes BEFORE applying (this is the fix)
-        let lsp_changes1 = editor.collect_lsp_changes(&batch1);
+        let lsp_changes1 = range_changes_of(editor.collect_lsp_changes(&batch1));
 
         // Verify first rename LSP positions are correct
         assert_eq!(
@@ -5835,7 +7148,12 @@ mod tests {
 
         // Apply first rename using bulk edit
         editor
-            .apply_events_to_buffer_as_bulk_edit(buffer_id, events1, "LSP Rename 1".to_string())
+            .apply_events_to_buffer_as_bulk_edit(
+                buffer_id,
+                events1,
+                "LSP Rename 1".to_string(),
+                None,
+            )
             .unwrap();
 
         // Verify buffer after first rename
@@ -5886,7 +7204,7 @@ mod tests {
         };
 
         // Collect LSP changes BEFORE applying (this is the fix)
-        let lsp_changes2 = editor.collect_lsp_changes(&batch2);
+        let lsp_changes2 = range_changes_of(editor.collect_lsp_changes(&batch2));
 
         // Verify second rename LSP positions are correct
         // THIS IS WHERE THE BUG WOULD MANIFEST - if positions are wrong,
@@ -5931,7 +7249,12 @@ mod tests {
 
         // Apply second rename using bulk edit
         editor
-            .apply_events_to_buffer_as_bulk_edit(buffer_id, events2, "LSP Rename 2".to_string())
+            .apply_events_to_buffer_as_bulk_edit(
+                buffer_id,
+                events2,
+                "LSP Rename 2".to_string(),
+                None,
+            )
             .unwrap();
 
         // Verify buffer after second rename
@@ -6025,4 +7348,60 @@ mod tests {
             .sum();
         assert!(view_state.tab_scroll_offset <= total_width);
     }
+
+    /// Entries listed without metadata get it once they are on screen, even
+    /// with the details columns hidden
+    #[test]
+    fn test_entry_metadata_fetched_for_rows_on_screen() {
+        let (dir_context, temp) = test_dir_context();
+        let root = temp.path().join("project");
+        std::fs::create_dir(&root).unwrap();
+        for i in 0..20 {
+            std::fs::write(root.join(format!("file{i:02}.txt")), "x".repeat(i)).unwrap();
+        }
+        let mut editor = Editor::new(
+            Config::default(),
+            80,
+            24,
+            dir_context,
+            crate::view::color_support::ColorCapability::TrueColor,
+        )
+        .unwrap();
+
+        let runtime = editor.tokio_runtime.as_ref().unwrap();
+        let fs_manager = Arc::clone(&editor.fs_manager);
+        let mut tree = runtime
+            .block_on(FileTree::new(root.clone(), Arc::clone(&fs_manager)))
+            .unwrap();
+        let root_id = tree.root_id();
+        tree.begin_expand(root_id).unwrap();
+        let entries = runtime.block_on(fs_manager.list_dir(root.clone())).unwrap();
+        assert!(entries.iter().all(|entry| entry.metadata.is_none()));
+        let mut view = FileTreeView::new(tree);
+        view.append_children(root_id, entries);
+        view.tree_mut().finish_expand(root_id);
+        view.set_viewport_height(10);
+        assert!(!view.show_details());
+        editor.file_explorer = Some(view);
+
+        editor.request_entry_metadata();
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(10);
+        while !editor.pending_entry_metadata.is_empty() {
+            assert!(
+                std::time::Instant::now() < deadline,
+                "metadata never arrived"
+            );
+            std::thread::sleep(std::time::Duration::from_millis(10));
+            editor.process_async_messages();
+        }
+
+        let tree = editor.file_explorer.as_ref().unwrap().tree();
+        let rows = tree.get_visible_nodes();
+        let has_metadata = |id: &NodeId| tree.get_node(*id).unwrap().entry.metadata.is_some();
+        assert!(rows[..10].iter().all(has_metadata));
+        // Rows below the viewport aren't stat'ed
+        assert!(!rows[10..].iter().any(has_metadata));
+        let file03 = tree.get_node_by_path(&root.join("file03.txt")).unwrap();
+        assert_eq!(file03.entry.metadata.as_ref().unwrap().size, Some(3));
+    }
 }
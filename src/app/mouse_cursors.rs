@@ -0,0 +1,333 @@
+//! Adding cursors with the mouse
+//!
+//! With the multi-cursor modifier (`mouse.multi_cursor_modifier`, Ctrl by
+//! default) a click adds a cursor, or removes the cursor that was clicked.
+//! With the other of Ctrl and Alt, dragging up or down replaces the cursors
+//! with one per line at the mouse column. The cursors are added with the same events as
+//! the keyboard commands, so undo treats them alike.
+
+use crossterm::event::KeyModifiers;
+use ratatui::layout::Rect;
+use rust_i18n::t;
+
+use super::Editor;
+use crate::config::MultiCursorModifier;
+use crate::input::keybindings::Action;
+use crate::input::multi_cursor::{add_cursor_at, AddCursorResult};
+use crate::model::event::{BufferId, CursorId, Event, SplitId};
+
+/// A column of cursors being placed by dragging
+#[derive(Debug, Clone)]
+pub(super) struct ColumnDrag {
+    split_id: SplitId,
+    buffer_id: BufferId,
+    content_rect: Rect,
+    /// Screen row the drag started on
+    start_row: u16,
+    /// Cursor placed by the press, kept on the start row
+    start_cursor: CursorId,
+    /// Cursors placed so far, replaced as the mouse moves
+    added: Vec<(CursorId, usize)>,
+}
+
+/// What a modified click does
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ModifiedClick {
+    ToggleCursor,
+    ColumnDrag,
+}
+
+impl Editor {
+    /// Handle a left click made with the multi-cursor or column modifier
+    ///
+    /// Returns false if the click isn't one, or isn't on buffer text, and
+    /// should be handled as a plain click.
+    pub(super) fn handle_multi_cursor_click(
+        &mut self,
+        col: u16,
+        row: u16,
+        modifiers: KeyModifiers,
+    ) -> std::io::Result<bool> {
+        let (toggle, column) = match self.config.mouse.multi_cursor_modifier {
+            MultiCursorModifier::Ctrl => (KeyModifiers::CONTROL, KeyModifiers::ALT),
+            MultiCursorModifier::Alt => (KeyModifiers::ALT, KeyModifiers::CONTROL),
+        };
+        let kind = if modifiers == toggle {
+            ModifiedClick::ToggleCursor
+        } else if modifiers == column {
+            ModifiedClick::ColumnDrag
+        } else {
+            return Ok(false);
+        };
+        let Some((split_id, buffer_id, content_rect)) = self.editor_text_at(col, row) else {
+            return Ok(false);
+        };
+        if self.is_terminal_buffer(buffer_id) {
+            return Ok(false);
+        }
+
+        match kind {
+            ModifiedClick::ToggleCursor => {
+                self.focus_split(split_id, buffer_id);
+                if let Some(position) =
+                    self.buffer_position_at(split_id, buffer_id, content_rect, col, row)
+                {
+                    self.toggle_cursor_at(position);
+                }
+            }
+            ModifiedClick::ColumnDrag => {
+                // The column replaces the cursors there were, and the press
+                // places the first one like a plain click
+                self.focus_split(split_id, buffer_id);
+                if self.active_state().cursors.count() > 1 {
+                    self.handle_action(Action::RemoveSecondaryCursors)?;
+                }
+                self.handle_editor_click(col, row, split_id, buffer_id, content_rect)?;
+                self.mouse_state.dragging_text_selection = false;
+                self.mouse_state.drag_selection_split = None;
+                self.mouse_state.drag_selection_anchor = None;
+                let start_cursor = self.active_state().cursors.primary_id();
+                self.mouse_state.column_drag = Some(ColumnDrag {
+                    split_id,
+                    buffer_id,
+                    content_rect,
+                    start_row: row,
+                    start_cursor,
+                    added: Vec::new(),
+                });
+            }
+        }
+        Ok(true)
+    }
+
+    /// Split and content area of the buffer text under the mouse, if nothing
+    /// is drawn over it
+    fn editor_text_at(&self, col: u16, row: u16) -> Option<(SplitId, BufferId, Rect)> {
+        let contains = |rect: &Rect| {
+            col >= rect.x
+                && col < rect.x + rect.width
+                && row >= rect.y
+                && row < rect.y + rect.height
+        };
+        if self.menu_state.active_menu.is_some()
+            || self.tab_context_menu.is_some()
+            || self.is_file_open_active()
+            || self.is_mouse_over_any_popup(col, row)
+            || self
+                .cached_layout
+                .suggestions_area
+                .as_ref()
+                .is_some_and(|(rect, ..)| contains(rect))
+        {
+            return None;
+        }
+        self.cached_layout
+            .split_areas
+            .iter()
+            .find(|(_, _, content_rect, ..)| contains(content_rect))
+            .map(|(split_id, buffer_id, content_rect, ..)| (*split_id, *buffer_id, *content_rect))
+    }
+
+    /// Byte position of the text drawn at a screen cell, if the row shows
+    /// buffer text
+    fn buffer_position_at(
+        &self,
+        split_id: SplitId,
+        buffer_id: BufferId,
+        content_rect: Rect,
+        col: u16,
+        row: u16,
+    ) -> Option<usize> {
+        let mappings = self.cached_layout.view_line_mappings.get(&split_id)?;
+        if row < content_rect.y || (row - content_rect.y) as usize >= mappings.len() {
+            return None;
+        }
+        let gutter_width = self.buffers.get(&buffer_id)?.margins.left_total_width() as u16;
+        Self::screen_to_buffer_position(
+            col,
+            row,
+            content_rect,
+            gutter_width,
+            &Some(mappings.clone()),
+            0,
+            true,
+        )
+    }
+
+    /// Add a cursor at `position` in the active buffer, or remove the cursor
+    /// already there
+    fn toggle_cursor_at(&mut self, position: usize) {
+        let state = self.active_state();
+        let existing = state
+            .cursors
+            .iter()
+            .find(|(_, cursor)| cursor.position == position)
+            .map(|(id, cursor)| (id, cursor.anchor));
+
+        if let Some((cursor_id, anchor)) = existing {
+            // The last cursor stays
+            if state.cursors.count() == 1 {
+                return;
+            }
+            let event = Event::RemoveCursor {
+                cursor_id,
+                position,
+                anchor,
+            };
+            self.active_event_log_mut().append(event.clone());
+            self.apply_event_to_active_buffer(&event);
+            let count = self.active_state().cursors.count();
+            self.set_status_message(t!("clipboard.removed_cursor", count = count).to_string());
+            return;
+        }
+
+        match add_cursor_at(state, position) {
+            AddCursorResult::Success {
+                cursor,
+                total_cursors,
+            } => {
+                let event = Event::AddCursor {
+                    cursor_id: next_cursor_id(self),
+                    position: cursor.position,
+                    anchor: cursor.anchor,
+                };
+                self.active_event_log_mut().append(event.clone());
+                self.apply_event_to_active_buffer(&event);
+                self.set_status_message(
+                    t!("clipboard.added_cursor_click", count = total_cursors).to_string(),
+                );
+            }
+            AddCursorResult::Failed { message } => self.set_status_message(message),
+        }
+    }
+
+    /// Place one cursor per line from the drag's start row to `row`, at the
+    /// mouse column
+    pub(super) fn handle_column_drag(&mut self, col: u16, row: u16) {
+        let Some(mut drag) = self.mouse_state.column_drag.take() else {
+            return;
+        };
+        if self.active_buffer() != drag.buffer_id {
+            return;
+        }
+
+        // Take back the cursors placed for the previous mouse position
+        for (cursor_id, position) in drag.added.drain(..) {
+            self.apply_event_to_active_buffer(&Event::RemoveCursor {
+                cursor_id,
+                position,
+                anchor: None,
+            });
+        }
+
+        // Keep the cursor on the start row under the mouse too
+        if let Some(position) = self.buffer_position_at(
+            drag.split_id,
+            drag.buffer_id,
+            drag.content_rect,
+            col,
+            drag.start_row,
+        ) {
+            let old_position = self
+                .active_state()
+                .cursors
+                .get(drag.start_cursor)
+                .map_or(position, |cursor| cursor.position);
+            self.apply_event_to_active_buffer(&Event::MoveCursor {
+                cursor_id: drag.start_cursor,
+                old_position,
+                new_position: position,
+                old_anchor: None,
+                new_anchor: None,
+                old_sticky_column: 0,
+                new_sticky_column: 0,
+            });
+        }
+
+        let rows: Vec<u16> = if row < drag.start_row {
+            (row..drag.start_row).rev().collect()
+        } else {
+            (drag.start_row + 1..=row).collect()
+        };
+        let mut limit_message = None;
+        for line_row in rows {
+            let Some(position) = self.buffer_position_at(
+                drag.split_id,
+                drag.buffer_id,
+                drag.content_rect,
+                col,
+                line_row,
+            ) else {
+                break;
+            };
+            let state = self.active_state();
+            if state.cursors.iter().any(|(_, c)| c.position == position) {
+                continue;
+            }
+            match add_cursor_at(state, position) {
+                AddCursorResult::Success { .. } => {
+                    let cursor_id = next_cursor_id(self);
+                    self.apply_event_to_active_buffer(&Event::AddCursor {
+                        cursor_id,
+                        position,
+                        anchor: None,
+                    });
+                    drag.added.push((cursor_id, position));
+                }
+                AddCursorResult::Failed { message } => {
+                    limit_message = Some(message);
+                    break;
+                }
+            }
+        }
+
+        let count = self.active_state().cursors.count();
+        match limit_message {
+            Some(message) => self.set_status_message(message),
+            None if !drag.added.is_empty() => self
+                .set_status_message(t!("clipboard.added_cursor_column", count = count).to_string()),
+            None => {}
+        }
+        self.mouse_state.column_drag = Some(drag);
+    }
+
+    /// Record the cursors of a finished column drag as one undoable step
+    pub(super) fn finish_column_drag(&mut self) {
+        let Some(drag) = self.mouse_state.column_drag.take() else {
+            return;
+        };
+        if drag.added.is_empty() {
+            return;
+        }
+        let Some(event_log) = self.event_logs.get_mut(&drag.buffer_id) else {
+            return;
+        };
+        let events = drag
+            .added
+            .into_iter()
+            .map(|(cursor_id, position)| Event::AddCursor {
+                cursor_id,
+                position,
+                anchor: None,
+            })
+            .collect();
+        event_log.append(Event::Batch {
+            events,
+            description: "Add cursors".to_string(),
+        });
+    }
+}
+
+/// ID for a new cursor in the active buffer
+///
+/// Cursors removed by clicking leave gaps in the IDs, so the count of
+/// cursors may already be taken.
+fn next_cursor_id(editor: &Editor) -> CursorId {
+    editor
+        .active_state()
+        .cursors
+        .iter()
+        .map(|(id, _)| id.0 + 1)
+        .max()
+        .map_or(CursorId(0), CursorId)
+}
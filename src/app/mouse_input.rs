@@ -20,12 +20,31 @@ impl Editor {
     pub fn handle_mouse(
         &mut self,
         mouse_event: crossterm::event::MouseEvent,
+    ) -> std::io::Result<bool> {
+        self.handle_mouse_repeated(mouse_event, 1)
+    }
+
+    /// Handle a mouse event that arrived `repeat` times in a row.
+    ///
+    /// The event loop coalesces bursts of wheel events (e.g. from trackpads) into
+    /// one event; wheel scrolling moves `repeat` steps at once, other events are
+    /// handled once.
+    /// Returns true if a re-render is needed.
+    pub fn handle_mouse_repeated(
+        &mut self,
+        mouse_event: crossterm::event::MouseEvent,
+        repeat: u16,
     ) -> std::io::Result<bool> {
         use crossterm::event::{MouseButton, MouseEventKind};
 
         let col = mouse_event.column;
         let row = mouse_event.row;
 
+        // Clicking anywhere keeps the text of a rename in buffer and ends it
+        if matches!(mouse_event.kind, MouseEventKind::Down(_)) {
+            self.commit_linked_rename();
+        }
+
         // Detect double-click for left button down events (used by all handlers)
         let is_double_click = if matches!(mouse_event.kind, MouseEventKind::Down(MouseButton::Left))
         {
@@ -86,6 +105,10 @@ impl Editor {
 
         match mouse_event.kind {
             MouseEventKind::Down(MouseButton::Left) => {
+                if self.handle_multi_cursor_click(col, row, mouse_event.modifiers)? {
+                    needs_render = true;
+                    return Ok(needs_render);
+                }
                 if is_double_click {
                     // Double click detected - both clicks within time threshold AND at same position
                     self.handle_mouse_double_click(col, row)?;
@@ -125,6 +148,7 @@ impl Editor {
                 self.mouse_state.drag_start_ratio = None;
                 self.mouse_state.dragging_file_explorer = false;
                 self.mouse_state.drag_start_explorer_width = None;
+                self.finish_column_drag();
                 // Clear text selection drag state (selection remains in cursor)
                 self.mouse_state.dragging_text_selection = false;
                 self.mouse_state.drag_selection_split = None;
@@ -173,38 +197,48 @@ impl Editor {
 
                 // Track LSP hover state for mouse-triggered hover popups
                 self.update_lsp_hover_state(col, row);
+
+                // Tell plugins which text the mouse is over, and close their
+                // anchored popup once it moves away
+                if self.handle_mouse_moved_in_buffer(col, row) {
+                    needs_render = true;
+                }
             }
-            MouseEventKind::ScrollUp => {
+            MouseEventKind::ScrollUp | MouseEventKind::ScrollDown => {
+                let lines = (self.config.mouse.scroll_lines * repeat as usize) as i32;
+                let delta = if mouse_event.kind == MouseEventKind::ScrollUp {
+                    -lines
+                } else {
+                    lines
+                };
                 // Check if file browser is active and should handle scroll
-                if self.is_file_open_active() && self.handle_file_open_scroll(-3) {
+                if self.is_file_open_active() && self.handle_file_open_scroll(delta) {
                     needs_render = true;
                 } else if self.is_mouse_over_any_popup(col, row) {
                     // Scroll the popup content (works for all popups including completion)
-                    self.scroll_popup(-3);
+                    self.scroll_popup(delta);
                     needs_render = true;
                 } else {
                     // Dismiss hover/signature help popups on scroll
                     self.dismiss_transient_popups();
-                    self.handle_mouse_scroll(col, row, -3)?;
+                    self.handle_mouse_scroll(col, row, delta)?;
                     // Sync viewport from SplitViewState to EditorState so rendering sees the scroll
                     self.sync_split_view_state_to_editor_state();
                     needs_render = true;
                 }
             }
-            MouseEventKind::ScrollDown => {
-                // Check if file browser is active and should handle scroll
-                if self.is_file_open_active() && self.handle_file_open_scroll(3) {
-                    needs_render = true;
-                } else if self.is_mouse_over_any_popup(col, row) {
-                    // Scroll the popup content (works for all popups including completion)
-                    self.scroll_popup(3);
-                    needs_render = true;
+            MouseEventKind::ScrollLeft | MouseEventKind::ScrollRight => {
+                let columns =
+                    (self.config.mouse.horizontal_scroll_columns * repeat as usize) as isize;
+                let delta = if mouse_event.kind == MouseEventKind::ScrollLeft {
+                    -columns
                 } else {
-                    // Dismiss hover/signature help popups on scroll
+                    columns
+                };
+                // Popups and the file browser don't scroll sideways
+                if !self.is_mouse_over_any_popup(col, row) {
                     self.dismiss_transient_popups();
-                    self.handle_mouse_scroll(col, row, 3)?;
-                    // Sync viewport from SplitViewState to EditorState so rendering sees the scroll
-                    self.sync_split_view_state_to_editor_state();
+                    self.handle_mouse_horizontal_scroll(col, row, delta);
                     needs_render = true;
                 }
             }
@@ -467,7 +501,7 @@ impl Editor {
         }
 
         // Start tracking new hover position
-        self.mouse_state.lsp_hover_state = Some((byte_pos, std::time::Instant::now(), col, row));
+        self.mouse_state.lsp_hover_state = Some((byte_pos, self.time_source.now(), col, row));
         self.mouse_state.lsp_hover_request_sent = false;
     }
 
@@ -488,7 +522,7 @@ impl Editor {
     }
 
     /// Check if mouse position is over any popup (including non-transient ones like completion)
-    fn is_mouse_over_any_popup(&self, col: u16, row: u16) -> bool {
+    pub(super) fn is_mouse_over_any_popup(&self, col: u16, row: u16) -> bool {
         // Check if there's any popup showing
         if !self.active_state().popups.is_visible() {
             return false;
@@ -859,6 +893,11 @@ impl Editor {
             }
         }
 
+        // Notifications are drawn over everything else
+        if self.handle_notification_click(col, row) {
+            return Ok(());
+        }
+
         // Check if click is on suggestions (command palette, autocomplete)
         if let Some((inner_rect, start_idx, _visible_count, total_count)) =
             &self.cached_layout.suggestions_area.clone()
@@ -912,6 +951,12 @@ impl Editor {
             }
         }
 
+        // A click outside the explorer's context menu closes it
+        if self.is_explorer_context_menu_popup() && !self.is_mouse_over_any_popup(col, row) {
+            self.hide_popup();
+            self.handle_explorer_context_menu_response(None);
+        }
+
         // Check if click is on the file browser popup
         if self.is_file_open_active() {
             if self.handle_file_open_click(col, row) {
@@ -1045,6 +1090,24 @@ impl Editor {
                         return self.handle_action(Action::ShowWarnings);
                     }
                 }
+
+                // Check blocked commands badge - click opens the review
+                if let Some((blocked_row, blocked_start, blocked_end)) =
+                    self.cached_layout.status_bar_blocked_area
+                {
+                    if row == blocked_row && col >= blocked_start && col < blocked_end {
+                        return self.handle_action(Action::ReviewBlockedCommands);
+                    }
+                }
+
+                // Check progress indicator - click cancels the operation
+                if let Some((progress_row, progress_start, progress_end)) =
+                    self.cached_layout.status_bar_progress_area
+                {
+                    if row == progress_row && col >= progress_start && col < progress_end {
+                        return self.handle_action(Action::CancelProgress);
+                    }
+                }
             }
         }
 
@@ -1191,6 +1254,18 @@ impl Editor {
             return Ok(());
         }
 
+        // Clicking a split's winbar focuses the split
+        let winbar_click = self
+            .cached_layout
+            .winbar_areas
+            .iter()
+            .find(|(_, _, rect)| row == rect.y && col >= rect.x && col < rect.x + rect.width)
+            .map(|(split_id, buffer_id, _)| (*split_id, *buffer_id));
+        if let Some((split_id, buffer_id)) = winbar_click {
+            self.focus_split(split_id, buffer_id);
+            return Ok(());
+        }
+
         // Check if click is in editor content area
         tracing::debug!(
             "handle_mouse_click: checking {} split_areas for click at ({}, {})",
@@ -1270,6 +1345,12 @@ impl Editor {
             return Ok(());
         }
 
+        // If placing a column of cursors
+        if self.mouse_state.column_drag.is_some() {
+            self.handle_column_drag(col, row);
+            return Ok(());
+        }
+
         // If dragging to select text
         if self.mouse_state.dragging_text_selection {
             self.handle_text_selection_drag(col, row)?;
@@ -1462,6 +1543,28 @@ impl Editor {
             }
         }
 
+        // A click elsewhere closes the explorer's context menu
+        if self.is_explorer_context_menu_popup() {
+            if self.is_mouse_over_any_popup(col, row) {
+                return Ok(());
+            }
+            self.hide_popup();
+            self.handle_explorer_context_menu_response(None);
+        }
+
+        // Check if right-click is on a file explorer entry
+        if let Some(explorer_area) = self.cached_layout.file_explorer_area {
+            if col >= explorer_area.x
+                && col < explorer_area.x + explorer_area.width
+                && row >= explorer_area.y
+                && row < explorer_area.y + explorer_area.height
+            {
+                self.tab_context_menu = None;
+                self.open_explorer_context_menu_at(col, row);
+                return Ok(());
+            }
+        }
+
         // Check if right-click is on a tab
         let tab_click = self.cached_layout.tab_areas.iter().find_map(
             |(split_id, buffer_id, tab_row, start_col, end_col, _close_start)| {
@@ -0,0 +1,384 @@
+//! Named layouts
+//!
+//! "Save Layout As" stores the current split tree, with each split's tabs,
+//! cursor and scroll, and the terminals and plugin panels shown in it, under
+//! a name (see [`NamedLayouts`]). "Switch Layout" rebuilds a saved tree
+//! through the session restore path: files that aren't open are opened, ones
+//! that no longer exist are skipped, and buffers the layout doesn't show stay
+//! open in the background. The layout a switch replaces is kept as the
+//! "previous" entry, so switching to it undoes the switch.
+
+use std::collections::{HashMap, HashSet};
+
+use rust_i18n::t;
+
+use super::session::{collect_file_paths_from_states, get_first_leaf_buffer};
+use super::Editor;
+use crate::input::commands::Suggestion;
+use crate::input::keybindings::KeyContext;
+use crate::model::event::{BufferId, SplitId};
+use crate::session::{NamedLayout, NamedLayouts};
+use crate::view::notifications::NotificationSeverity;
+use crate::view::prompt::{Prompt, PromptType};
+use crate::view::split::SplitViewState;
+
+/// Files listed in a layout's description before the rest are counted
+const MAX_LISTED_FILES: usize = 6;
+
+/// Picker value of the "previous" entry; saved layouts never have an empty name
+const PREVIOUS_LAYOUT: &str = "";
+
+impl Editor {
+    /// Load the named layouts for the working directory, reporting a failure
+    fn load_named_layouts(&mut self) -> Option<NamedLayouts> {
+        match NamedLayouts::load(&self.dir_context.sessions_dir(), &self.working_dir) {
+            Ok(layouts) => Some(layouts),
+            Err(e) => {
+                self.set_status_message(
+                    t!("layout.load_failed", error = e.to_string()).to_string(),
+                );
+                None
+            }
+        }
+    }
+
+    /// Capture the current split arrangement under `name`
+    fn capture_named_layout(&self, name: &str) -> NamedLayout {
+        let session = self.capture_session();
+        NamedLayout {
+            name: name.to_string(),
+            split_layout: session.split_layout,
+            active_split_id: session.active_split_id,
+            split_states: session.split_states,
+            terminals: session.terminals,
+            panels: session.panels,
+        }
+    }
+
+    /// Ask for the name to save the current layout under
+    pub fn start_save_layout_prompt(&mut self) {
+        self.start_prompt(
+            t!("layout.save_prompt").to_string(),
+            PromptType::SaveLayoutAs,
+        );
+    }
+
+    /// Save the current layout under `name`, replacing a layout of that name
+    pub(super) fn save_layout_as(&mut self, name: &str) {
+        let name = name.trim();
+        if name.is_empty() {
+            self.set_status_message(t!("layout.empty_name").to_string());
+            return;
+        }
+        let Some(mut layouts) = self.load_named_layouts() else {
+            return;
+        };
+        let replaced = layouts.get(name).is_some();
+        layouts.insert(self.capture_named_layout(name));
+        if let Err(e) = layouts.save(&self.dir_context.sessions_dir(), &self.working_dir) {
+            self.set_status_message(t!("layout.save_failed", error = e.to_string()).to_string());
+            return;
+        }
+        let message = if replaced {
+            t!("layout.replaced", name = name)
+        } else {
+            t!("layout.saved", name = name)
+        };
+        self.set_status_message(message.to_string());
+    }
+
+    /// Show a picker of the saved layouts, with the previous layout first
+    pub fn start_switch_layout_prompt(&mut self) {
+        let Some(layouts) = self.load_named_layouts() else {
+            return;
+        };
+        let mut suggestions = Vec::new();
+        if let Some(previous) = &layouts.previous {
+            suggestions.push(layout_suggestion(
+                t!("layout.previous").to_string(),
+                PREVIOUS_LAYOUT,
+                previous,
+            ));
+        }
+        suggestions.extend(
+            layouts
+                .layouts
+                .iter()
+                .map(|layout| layout_suggestion(layout.name.clone(), &layout.name, layout)),
+        );
+        if suggestions.is_empty() {
+            self.set_status_message(t!("layout.none_saved").to_string());
+            return;
+        }
+
+        self.prompt = Some(Prompt::with_suggestions(
+            t!("layout.switch_prompt").to_string(),
+            PromptType::SwitchLayout,
+            suggestions,
+        ));
+        if let Some(prompt) = self.prompt.as_mut() {
+            prompt.selected_suggestion = Some(0);
+        }
+    }
+
+    /// Show a picker of the saved layouts to delete one
+    pub fn start_delete_layout_prompt(&mut self) {
+        let Some(layouts) = self.load_named_layouts() else {
+            return;
+        };
+        if layouts.layouts.is_empty() {
+            self.set_status_message(t!("layout.none_saved").to_string());
+            return;
+        }
+        let suggestions = layouts
+            .layouts
+            .iter()
+            .map(|layout| layout_suggestion(layout.name.clone(), &layout.name, layout))
+            .collect();
+
+        self.prompt = Some(Prompt::with_suggestions(
+            t!("layout.delete_prompt").to_string(),
+            PromptType::DeleteLayout,
+            suggestions,
+        ));
+        if let Some(prompt) = self.prompt.as_mut() {
+            prompt.selected_suggestion = Some(0);
+        }
+    }
+
+    /// Delete the saved layout called `name`
+    pub(super) fn delete_layout(&mut self, name: &str) {
+        let Some(mut layouts) = self.load_named_layouts() else {
+            return;
+        };
+        if !layouts.remove(name) {
+            self.set_status_message(t!("layout.not_found", name = name).to_string());
+            return;
+        }
+        if let Err(e) = layouts.save(&self.dir_context.sessions_dir(), &self.working_dir) {
+            self.set_status_message(t!("layout.save_failed", error = e.to_string()).to_string());
+            return;
+        }
+        self.set_status_message(t!("layout.deleted", name = name).to_string());
+    }
+
+    /// Switch to the saved layout called `name`, or to the previous layout
+    /// when `name` is empty
+    pub(super) fn switch_layout(&mut self, name: &str) {
+        let Some(layouts) = self.load_named_layouts() else {
+            return;
+        };
+        let layout = if name == PREVIOUS_LAYOUT {
+            layouts.previous.clone()
+        } else {
+            layouts.get(name).cloned()
+        };
+        match layout {
+            Some(layout) => self.apply_named_layout(layouts, layout),
+            None if name == PREVIOUS_LAYOUT => {
+                self.set_status_message(t!("layout.no_previous").to_string())
+            }
+            None => self.set_status_message(t!("layout.not_found", name = name).to_string()),
+        }
+    }
+
+    /// Switch to the `index`th saved layout (1-based, in the order saved)
+    pub(super) fn switch_layout_at(&mut self, index: usize) {
+        let Some(layouts) = self.load_named_layouts() else {
+            return;
+        };
+        match index
+            .checked_sub(1)
+            .and_then(|i| layouts.layouts.get(i))
+            .cloned()
+        {
+            Some(layout) => self.apply_named_layout(layouts, layout),
+            None => self.set_status_message(t!("layout.no_index", index = index).to_string()),
+        }
+    }
+
+    /// Replace the split arrangement with `layout`, keeping the current one
+    /// as the previous layout
+    fn apply_named_layout(&mut self, mut layouts: NamedLayouts, layout: NamedLayout) {
+        layouts.previous = Some(self.capture_named_layout(PREVIOUS_LAYOUT));
+        if let Err(e) = layouts.save(&self.dir_context.sessions_dir(), &self.working_dir) {
+            tracing::warn!("Failed to save the previous layout: {}", e);
+        }
+
+        self.begin_layout_restore();
+        if self.is_distraction_free() {
+            self.exit_distraction_free();
+        }
+        if self.split_manager.is_maximized() {
+            let _ = self.split_manager.unmaximize_split();
+        }
+
+        // Open the layout's files in the background
+        let open_before: HashSet<BufferId> = self.buffers.keys().copied().collect();
+        let mut path_to_buffer = HashMap::new();
+        let mut skipped: Vec<String> = Vec::new();
+        for rel_path in collect_file_paths_from_states(&layout.split_states) {
+            let abs_path = self.working_dir.join(&rel_path);
+            if !abs_path.exists() {
+                skipped.push(rel_path.display().to_string());
+                continue;
+            }
+            match self.open_file_no_focus(&abs_path) {
+                Ok(buffer_id) => {
+                    path_to_buffer.insert(rel_path, buffer_id);
+                }
+                Err(e) => {
+                    tracing::warn!("Failed to open {:?} for layout: {}", abs_path, e);
+                    skipped.push(rel_path.display().to_string());
+                }
+            }
+        }
+        let opened = path_to_buffer
+            .values()
+            .filter(|id| !open_before.contains(id))
+            .count();
+
+        // Terminals are only shown if they are still running
+        let mut terminal_buffers = HashMap::new();
+        for terminal in &layout.terminals {
+            let live = self
+                .terminal_buffers
+                .iter()
+                .find(|(_, terminal_id)| {
+                    self.terminal_backing_files.get(terminal_id) == Some(&terminal.backing_path)
+                })
+                .map(|(&buffer_id, _)| buffer_id);
+            match live {
+                Some(buffer_id) => {
+                    terminal_buffers.insert(terminal.terminal_index, buffer_id);
+                }
+                None => skipped.push(t!("layout.closed_terminal").to_string()),
+            }
+        }
+
+        // Panels that were closed come back as placeholders for their plugin to fill
+        let mut panel_buffers = HashMap::new();
+        let mut restored_panels = Vec::new();
+        for panel in &layout.panels {
+            let open = self
+                .persistent_panels
+                .iter()
+                .find(|(buffer_id, open)| {
+                    open.plugin == panel.plugin
+                        && open.panel_id == panel.panel_id
+                        && self.buffers.contains_key(buffer_id)
+                })
+                .map(|(&buffer_id, _)| buffer_id);
+            let buffer_id = open.unwrap_or_else(|| {
+                let buffer_id = self.restore_panel_from_session(panel);
+                restored_panels.push(buffer_id);
+                buffer_id
+            });
+            panel_buffers.insert(panel.panel_index, buffer_id);
+        }
+
+        // Collapse to one split showing the layout's first leaf; the buffers
+        // of the closed splits stay open
+        let first_buffer = get_first_leaf_buffer(
+            &layout.split_layout,
+            &path_to_buffer,
+            &terminal_buffers,
+            &panel_buffers,
+        )
+        .unwrap_or(self.active_buffer());
+        let active_split = self.split_manager.active_split();
+        for split_id in self.split_manager.root().leaf_split_ids() {
+            if split_id != active_split && self.split_manager.close_split(split_id).is_ok() {
+                self.split_view_states.remove(&split_id);
+            }
+        }
+        let _ = self
+            .split_manager
+            .set_split_buffer(active_split, first_buffer);
+        let mut view_state =
+            SplitViewState::with_buffer(self.terminal_width, self.terminal_height, first_buffer);
+        view_state.viewport.line_wrap_enabled = self.config.editor.line_wrap;
+        self.split_view_states.insert(active_split, view_state);
+
+        let mut split_id_map: HashMap<usize, SplitId> = HashMap::new();
+        self.restore_split_node(
+            &layout.split_layout,
+            &path_to_buffer,
+            &terminal_buffers,
+            &panel_buffers,
+            &layout.split_states,
+            &mut split_id_map,
+            true,
+        );
+        self.request_panel_restores(&restored_panels);
+        if let Some(&split_id) = split_id_map.get(&layout.active_split_id) {
+            self.split_manager.set_active_split(split_id);
+        }
+        self.terminal_mode = false;
+        self.key_context = KeyContext::Normal;
+        self.resize_visible_terminals();
+        self.finish_layout_restore("layout", true);
+
+        let name = if layout.name == PREVIOUS_LAYOUT {
+            t!("layout.previous").to_string()
+        } else {
+            layout.name.clone()
+        };
+        if skipped.is_empty() {
+            self.notify(
+                NotificationSeverity::Info,
+                t!("layout.switched", name = name, opened = opened),
+            );
+        } else {
+            self.notify(
+                NotificationSeverity::Warning,
+                t!(
+                    "layout.switched_skipped",
+                    name = name,
+                    opened = opened,
+                    count = skipped.len(),
+                    skipped = skipped.join(", ")
+                ),
+            );
+        }
+    }
+}
+
+/// Picker entry for a layout, describing its splits and files
+fn layout_suggestion(text: String, value: &str, layout: &NamedLayout) -> Suggestion {
+    let files = collect_file_paths_from_states(&layout.split_states);
+    let mut listed: Vec<String> = files
+        .iter()
+        .take(MAX_LISTED_FILES)
+        .map(|path| path.display().to_string())
+        .collect();
+    if files.len() > MAX_LISTED_FILES {
+        listed.push(
+            t!(
+                "session.snapshot_more_files",
+                count = files.len() - MAX_LISTED_FILES
+            )
+            .to_string(),
+        );
+    }
+    let files = if listed.is_empty() {
+        t!("session.snapshot_no_files").to_string()
+    } else {
+        listed.join(", ")
+    };
+    Suggestion {
+        text,
+        description: Some(
+            t!(
+                "layout.entry",
+                count = layout.split_layout.leaf_count(),
+                files = files
+            )
+            .to_string(),
+        ),
+        value: Some(value.to_string()),
+        disabled: false,
+        keybinding: None,
+        source: None,
+    }
+}
@@ -0,0 +1,205 @@
+//! Notifications posted by the editor and by plugins
+//!
+//! The stack itself lives in [`crate::view::notifications`]; this module
+//! posts to it, expires notifications on the editor clock, and routes keys
+//! and clicks on them. Choosing a button (or a notification with buttons
+//! going away unanswered) fires the `action_popup_result` hook, with the
+//! notification id as `popup_id` and "dismissed" as the action when none was
+//! chosen, just like plugin action popups.
+
+use crate::primitives::text_property::TextPropertyEntry;
+use crate::services::plugins::hooks::HookArgs;
+use crate::view::notifications::{
+    next_notification_id, render_notifications, Notification, NotificationSeverity,
+};
+use crossterm::event::{KeyCode, KeyModifiers};
+use ratatui::layout::Rect;
+use ratatui::Frame;
+use rust_i18n::t;
+use serde_json::json;
+
+use super::Editor;
+
+/// Display name of the notification history buffer
+pub(crate) const NOTIFICATION_HISTORY_BUFFER_NAME: &str = "*Notifications*";
+
+impl Editor {
+    /// Show a notification with the default timeout, returning its id
+    pub fn notify(&mut self, severity: NotificationSeverity, message: impl Into<String>) -> String {
+        let id = next_notification_id();
+        self.post_notification(Notification::new(id.clone(), severity, message.into()));
+        id
+    }
+
+    /// Queue a notification (one with the same id is replaced)
+    pub fn post_notification(&mut self, notification: Notification) {
+        tracing::debug!(
+            "Notification {} ({:?}): {}",
+            notification.id,
+            notification.severity,
+            notification.message
+        );
+        let now = self.time_source.now();
+        self.notifications.push(notification, now);
+    }
+
+    /// Queued and shown notifications, and the history
+    pub fn notifications(&self) -> &crate::view::notifications::NotificationStack {
+        &self.notifications
+    }
+
+    /// Remove notifications whose timeout passed
+    ///
+    /// Returns true if any was removed and the screen needs a redraw.
+    pub fn check_notification_timer(&mut self) -> bool {
+        let expired = self.notifications.expire(self.time_source.now());
+        for notification in &expired {
+            self.fire_notification_action(notification, None);
+        }
+        !expired.is_empty()
+    }
+
+    /// Remove a notification, firing the hook with `action` (or "dismissed")
+    fn dismiss_notification(&mut self, id: &str, action: Option<&str>) {
+        let now = self.time_source.now();
+        if let Some(notification) = self.notifications.dismiss(id, now) {
+            self.fire_notification_action(&notification, action);
+        }
+    }
+
+    fn fire_notification_action(&mut self, notification: &Notification, action: Option<&str>) {
+        if self.handle_save_retry_answer(&notification.id, action)
+            || self.handle_self_update_answer(&notification.id, action)
+            || self.handle_settings_import_answer(&notification.id, action)
+            || self.handle_progress_answer(&notification.id, action)
+        {
+            return;
+        }
+        if notification.actions.is_empty() {
+            return;
+        }
+        self.plugin_manager.run_hook(
+            "action_popup_result",
+            HookArgs::ActionPopupResult {
+                popup_id: notification.id.clone(),
+                action_id: action.unwrap_or("dismissed").to_string(),
+            },
+        );
+    }
+
+    /// Move keyboard focus to the notifications on screen
+    pub fn focus_notifications(&mut self) {
+        if !self.notifications.focus() {
+            self.set_status_message(t!("notification.none").to_string());
+        }
+    }
+
+    /// Handle a key while the notifications have focus
+    ///
+    /// Tab and the arrow keys move between buttons, Enter chooses the
+    /// focused one, Delete dismisses the focused notification, and Esc (or
+    /// any other key) gives focus back.
+    pub(super) fn handle_notification_key(&mut self, code: KeyCode, modifiers: KeyModifiers) {
+        match code {
+            KeyCode::Tab | KeyCode::Right | KeyCode::Down => self.notifications.move_focus(true),
+            KeyCode::BackTab | KeyCode::Left | KeyCode::Up => self.notifications.move_focus(false),
+            KeyCode::Enter | KeyCode::Char(' ') if modifiers.is_empty() => {
+                if let Some((id, action)) = self.notifications.focused() {
+                    let (id, action) = (id.to_string(), action.map(str::to_string));
+                    self.dismiss_notification(&id, action.as_deref());
+                }
+            }
+            KeyCode::Delete => {
+                if let Some((id, _)) = self.notifications.focused() {
+                    let id = id.to_string();
+                    self.dismiss_notification(&id, None);
+                }
+            }
+            _ => self.notifications.unfocus(),
+        }
+    }
+
+    /// Handle a click on a notification: a button is chosen, anywhere else
+    /// dismisses it
+    ///
+    /// Returns false if the click wasn't on a notification.
+    pub(super) fn handle_notification_click(&mut self, col: u16, row: u16) -> bool {
+        let layout = &self.cached_layout.notification_layout;
+        let hit = |rect: &Rect| {
+            col >= rect.x
+                && col < rect.x + rect.width
+                && row >= rect.y
+                && row < rect.y + rect.height
+        };
+        if let Some((id, action, _)) = layout.buttons.iter().find(|(_, _, rect)| hit(rect)) {
+            let (id, action) = (id.clone(), action.clone());
+            self.dismiss_notification(&id, Some(&action));
+            return true;
+        }
+        if let Some((id, _)) = layout.areas.iter().find(|(_, rect)| hit(rect)) {
+            let id = id.clone();
+            self.dismiss_notification(&id, None);
+            return true;
+        }
+        false
+    }
+
+    /// Draw the notifications above the status bar
+    pub(super) fn render_notifications(&mut self, frame: &mut Frame) {
+        let size = frame.area();
+        let bottom = self
+            .cached_layout
+            .status_bar_area
+            .map_or(size.y + size.height, |(row, _, _)| row);
+        let area = Rect::new(size.x, size.y, size.width, bottom.saturating_sub(size.y));
+        self.cached_layout.notification_layout =
+            render_notifications(frame, area, &self.notifications, &self.theme);
+    }
+
+    /// List recent notifications, newest first, in a read-only buffer
+    pub fn show_notification_history(&mut self) {
+        let existing = self
+            .buffer_metadata
+            .iter()
+            .find(|(_, m)| m.display_name == NOTIFICATION_HISTORY_BUFFER_NAME)
+            .map(|(id, _)| *id);
+        let buffer_id = existing.unwrap_or_else(|| {
+            let buffer_id = self.create_virtual_buffer(
+                NOTIFICATION_HISTORY_BUFFER_NAME.to_string(),
+                "special".to_string(),
+                true,
+            );
+            if let Some(state) = self.buffers.get_mut(&buffer_id) {
+                state.editing_disabled = true;
+                state.margins.set_line_numbers(false);
+            }
+            buffer_id
+        });
+
+        let mut entries =
+            vec![
+                TextPropertyEntry::text(format!("{}\n\n", t!("notification.history_title")))
+                    .with_property("type", json!("header")),
+            ];
+        if self.notifications.history().next().is_none() {
+            entries.push(TextPropertyEntry::text(format!(
+                "{}\n",
+                t!("notification.history_empty")
+            )));
+        }
+        for notification in self.notifications.history().rev() {
+            entries.push(
+                TextPropertyEntry::text(format!(
+                    "[{}] {}\n",
+                    notification.severity.label(),
+                    notification.message.replace('\n', " ")
+                ))
+                .with_property("id", json!(notification.id)),
+            );
+        }
+        if let Err(e) = self.set_virtual_buffer_content(buffer_id, entries) {
+            tracing::error!("Failed to render notification history: {}", e);
+        }
+        self.set_active_buffer(buffer_id);
+    }
+}
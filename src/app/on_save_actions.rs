@@ -1,7 +1,7 @@
 //! On-save action execution.
 //!
 //! This module handles running configured actions when files are saved,
-//! such as formatters, linters, and other tools.
+//! such as linters and other tools. Formatters run from `formatting`.
 
 use std::io::Write;
 use std::path::Path;
@@ -9,24 +9,25 @@ use std::process::{Command, Stdio};
 use std::time::Duration;
 
 use super::Editor;
-use crate::config::{FormatterConfig, OnSaveAction};
-use crate::model::event::Event;
+use crate::config::OnSaveAction;
+use crate::services::command_trust::CommandRequest;
 use crate::services::lsp::manager::detect_language;
 use rust_i18n::t;
 
-/// Result of running a formatter or on-save action
+/// Result of running an on-save action
 enum ActionResult {
-    /// Action ran successfully, contains output
-    Success(String),
+    /// Action ran successfully
+    Success,
     /// Command not found
-    CommandNotFound(String),
+    CommandNotFound,
+    /// Project command the user hasn't allowed, contains its command line
+    NotAllowed(String),
     /// Action failed with error
     Error(String),
 }
 
 impl Editor {
     /// Run on-save actions for the active buffer after a successful save.
-    /// This includes format-on-save (if enabled) and any on_save actions.
     /// Returns Ok(true) if actions ran successfully, Ok(false) if no actions,
     /// or Err with an error message.
     pub fn run_on_save_actions(&mut self) -> Result<bool, String> {
@@ -48,32 +49,6 @@ impl Editor {
 
         let mut ran_any_action = false;
 
-        // Run formatter if format_on_save is enabled
-        if lang_config.format_on_save {
-            if let Some(ref formatter) = lang_config.formatter {
-                match self.run_formatter(formatter, &path) {
-                    ActionResult::Success(output) => {
-                        self.replace_buffer_with_output(&output)?;
-                        // Re-save after formatting
-                        if let Err(e) = self.active_state_mut().buffer.save() {
-                            return Err(format!("Failed to re-save after format: {}", e));
-                        }
-                        self.active_event_log_mut().mark_saved();
-                        ran_any_action = true;
-                    }
-                    ActionResult::CommandNotFound(cmd) => {
-                        self.status_message = Some(format!(
-                            "Formatter '{}' not found (install it for auto-formatting)",
-                            cmd
-                        ));
-                    }
-                    ActionResult::Error(e) => {
-                        return Err(e);
-                    }
-                }
-            }
-        }
-
         // Run on_save actions (linters, etc.)
         let project_root = std::env::current_dir()
             .unwrap_or_else(|_| path.parent().unwrap_or(Path::new(".")).to_path_buf());
@@ -83,13 +58,18 @@ impl Editor {
                 continue;
             }
 
-            match self.run_on_save_action(action, &path, &project_root) {
-                ActionResult::Success(_) => {
+            match self.run_on_save_action(&language, action, &path, &project_root) {
+                ActionResult::Success => {
                     ran_any_action = true;
                 }
-                ActionResult::CommandNotFound(_) => {
+                ActionResult::CommandNotFound => {
                     // Skip missing optional commands silently
                 }
+                ActionResult::NotAllowed(command_line) => {
+                    self.set_status_message(
+                        t!("command_trust.held_back", command = command_line).to_string(),
+                    );
+                }
                 ActionResult::Error(e) => {
                     return Err(e);
                 }
@@ -99,180 +79,24 @@ impl Editor {
         Ok(ran_any_action)
     }
 
-    /// Format the current buffer using the configured formatter.
-    /// Returns Ok(()) if formatting succeeded, or Err with an error message.
-    pub fn format_buffer(&mut self) -> Result<(), String> {
-        let path = match self.active_state().buffer.file_path() {
-            Some(p) => p.to_path_buf(),
-            None => {
-                return Err(
-                    "Cannot format unsaved buffer (save first to detect language)".to_string(),
-                )
-            }
-        };
-
-        // Detect language for this file
-        let language = match detect_language(&path, &self.config.languages) {
-            Some(lang) => lang,
-            None => return Err("No language detected for this file".to_string()),
-        };
-
-        // Get formatter for this language
-        let formatter = self
-            .config
-            .languages
-            .get(&language)
-            .and_then(|lc| lc.formatter.clone());
-
-        let formatter = match formatter {
-            Some(f) => f,
-            None => return Err(format!("No formatter configured for {}", language)),
-        };
-
-        match self.run_formatter(&formatter, &path) {
-            ActionResult::Success(output) => {
-                self.replace_buffer_with_output(&output)?;
-                self.set_status_message(
-                    t!(
-                        "format.formatted_with",
-                        formatter = formatter.command.clone()
-                    )
-                    .to_string(),
-                );
-                Ok(())
-            }
-            ActionResult::CommandNotFound(cmd) => Err(format!("Formatter '{}' not found", cmd)),
-            ActionResult::Error(e) => Err(e),
-        }
-    }
-
-    /// Run a formatter on the current buffer content.
-    fn run_formatter(&mut self, formatter: &FormatterConfig, file_path: &Path) -> ActionResult {
-        let file_path_str = file_path.display().to_string();
-
-        // Check if command exists
-        if !command_exists(&formatter.command) {
-            return ActionResult::CommandNotFound(formatter.command.clone());
-        }
-
-        // Build the command
-        let shell = detect_shell();
-
-        // Build the full command string with arguments
-        let mut cmd_parts = vec![formatter.command.clone()];
-        for arg in &formatter.args {
-            cmd_parts.push(arg.replace("$FILE", &file_path_str));
-        }
-
-        let full_command = cmd_parts.join(" ");
-
-        // Get project root for working directory
-        let project_root = std::env::current_dir()
-            .unwrap_or_else(|_| file_path.parent().unwrap_or(Path::new(".")).to_path_buf());
-
-        // Set up the command
-        let mut cmd = Command::new(&shell);
-        cmd.args(["-c", &full_command])
-            .current_dir(&project_root)
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped());
-
-        if formatter.stdin {
-            cmd.stdin(Stdio::piped());
-        } else {
-            cmd.stdin(Stdio::null());
-        }
-
-        // Spawn the process
-        let mut child = match cmd.spawn() {
-            Ok(c) => c,
-            Err(e) => {
-                return ActionResult::Error(format!(
-                    "Failed to run '{}': {}",
-                    formatter.command, e
-                ));
-            }
-        };
-
-        // Write buffer content to stdin if configured
-        if formatter.stdin {
-            let content = self.active_state().buffer.to_string().unwrap_or_default();
-            if let Some(mut stdin) = child.stdin.take() {
-                if let Err(e) = stdin.write_all(content.as_bytes()) {
-                    return ActionResult::Error(format!("Failed to write to stdin: {}", e));
-                }
-            }
-        }
-
-        // Wait for the process with timeout
-        let timeout = Duration::from_millis(formatter.timeout_ms);
-        let start = std::time::Instant::now();
-
-        loop {
-            match child.try_wait() {
-                Ok(Some(status)) => {
-                    let output = match child.wait_with_output() {
-                        Ok(o) => o,
-                        Err(e) => {
-                            return ActionResult::Error(format!("Failed to get output: {}", e))
-                        }
-                    };
-
-                    if status.success() {
-                        return match String::from_utf8(output.stdout) {
-                            Ok(s) => ActionResult::Success(s),
-                            Err(e) => {
-                                ActionResult::Error(format!("Invalid UTF-8 in output: {}", e))
-                            }
-                        };
-                    } else {
-                        let stderr = String::from_utf8_lossy(&output.stderr);
-                        let stdout = String::from_utf8_lossy(&output.stdout);
-                        let error_output = if !stderr.is_empty() {
-                            stderr.trim().to_string()
-                        } else if !stdout.is_empty() {
-                            stdout.trim().to_string()
-                        } else {
-                            format!("exit code {:?}", status.code())
-                        };
-                        return ActionResult::Error(format!(
-                            "Formatter '{}' failed: {}",
-                            formatter.command, error_output
-                        ));
-                    }
-                }
-                Ok(None) => {
-                    if start.elapsed() > timeout {
-                        let _ = child.kill();
-                        return ActionResult::Error(format!(
-                            "Formatter '{}' timed out after {}ms",
-                            formatter.command, formatter.timeout_ms
-                        ));
-                    }
-                    std::thread::sleep(Duration::from_millis(10));
-                }
-                Err(e) => {
-                    return ActionResult::Error(format!(
-                        "Failed to wait for '{}': {}",
-                        formatter.command, e
-                    ));
-                }
-            }
-        }
-    }
-
     /// Run a single on-save action (linter, etc.).
     fn run_on_save_action(
         &mut self,
+        language: &str,
         action: &OnSaveAction,
         file_path: &Path,
         project_root: &Path,
     ) -> ActionResult {
         let file_path_str = file_path.display().to_string();
 
+        let request = CommandRequest::on_save(language, action);
+        if !self.command_gate.authorize(&request) {
+            return ActionResult::NotAllowed(request.command_line);
+        }
+
         // Check if command exists
         if !command_exists(&action.command) {
-            return ActionResult::CommandNotFound(action.command.clone());
+            return ActionResult::CommandNotFound;
         }
 
         // Build the command
@@ -347,12 +171,7 @@ impl Editor {
                     };
 
                     if status.success() {
-                        return match String::from_utf8(output.stdout) {
-                            Ok(s) => ActionResult::Success(s),
-                            Err(e) => {
-                                ActionResult::Error(format!("Invalid UTF-8 in output: {}", e))
-                            }
-                        };
+                        return ActionResult::Success;
                     } else {
                         let stderr = String::from_utf8_lossy(&output.stderr);
                         let stdout = String::from_utf8_lossy(&output.stdout);
@@ -388,68 +207,6 @@ impl Editor {
             }
         }
     }
-
-    /// Replace the active buffer's content with new output.
-    fn replace_buffer_with_output(&mut self, output: &str) -> Result<(), String> {
-        let cursor_id = self.active_state().cursors.primary_id();
-
-        // Get current buffer content
-        let buffer_content = self.active_state().buffer.to_string().unwrap_or_default();
-
-        // Only replace if content is different
-        if buffer_content == output {
-            return Ok(());
-        }
-
-        let buffer_len = buffer_content.len();
-
-        // Capture cursor position and selection state before replacement
-        let old_cursor_pos = self.active_state().cursors.primary().position;
-        let old_anchor = self.active_state().cursors.primary().anchor;
-        let old_sticky_column = self.active_state().cursors.primary().sticky_column;
-
-        // Delete all content and insert new
-        let delete_event = Event::Delete {
-            range: 0..buffer_len,
-            deleted_text: buffer_content,
-            cursor_id,
-        };
-        let insert_event = Event::Insert {
-            position: 0,
-            text: output.to_string(),
-            cursor_id,
-        };
-
-        // After delete+insert, cursor will be at output.len()
-        // Restore cursor to original position (or clamp to new buffer length)
-        let new_buffer_len = output.len();
-        let new_cursor_pos = old_cursor_pos.min(new_buffer_len);
-
-        // Only add MoveCursor event if position actually changes
-        let mut events = vec![delete_event, insert_event];
-        if new_cursor_pos != new_buffer_len {
-            let move_cursor_event = Event::MoveCursor {
-                cursor_id,
-                old_position: new_buffer_len, // Where cursor is after insert
-                new_position: new_cursor_pos,
-                old_anchor: None,
-                new_anchor: old_anchor.map(|a| a.min(new_buffer_len)),
-                old_sticky_column: 0,
-                new_sticky_column: old_sticky_column,
-            };
-            events.push(move_cursor_event);
-        }
-
-        // Apply as a batch for atomic undo
-        let batch = Event::Batch {
-            events,
-            description: "On-save format".to_string(),
-        };
-        self.active_event_log_mut().append(batch.clone());
-        self.apply_event_to_active_buffer(&batch);
-
-        Ok(())
-    }
 }
 
 /// Check if a command exists in the system PATH.
@@ -0,0 +1,154 @@
+//! Opening a path from outside the editor
+//!
+//! Command-line arguments, plugins and LSP locations name paths that may be
+//! anything. [`Editor::open_path`] classifies them first (see
+//! [`crate::services::open_target`]): files open in a buffer, directories are
+//! shown in the file explorer, missing files open as new buffers or, with
+//! `files.missing_files: "ask"`, after confirming, and FIFOs, sockets and
+//! devices are refused with a message instead of blocking on open.
+
+use std::collections::VecDeque;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use rust_i18n::t;
+
+use super::Editor;
+use crate::config::MissingFileMode;
+use crate::input::keybindings::KeyContext;
+use crate::model::event::BufferId;
+use crate::services::file_provider::is_remote_path;
+use crate::services::open_target::{self, OpenTarget};
+use crate::view::prompt::PromptType;
+
+/// Missing files waiting to be confirmed, asked about one at a time
+#[derive(Debug, Default)]
+pub(super) struct MissingFilePrompts {
+    queue: VecDeque<PathBuf>,
+}
+
+impl Editor {
+    /// Open `path` for the user, whatever it turns out to be
+    ///
+    /// Returns the buffer showing the file, or None when nothing was opened
+    /// yet: the path was a directory now shown in the file explorer, a
+    /// missing file waiting on "create it?", or a special file that was
+    /// refused with a status message. Relative paths are taken from the
+    /// working directory.
+    pub fn open_path(&mut self, path: &Path) -> io::Result<Option<BufferId>> {
+        if is_remote_path(path) {
+            return self.open_file(path).map(Some);
+        }
+        let resolved_path = if path.is_relative() {
+            self.working_dir.join(path)
+        } else {
+            path.to_path_buf()
+        };
+
+        match open_target::classify(&resolved_path)? {
+            OpenTarget::File => self.open_file(&resolved_path).map(Some),
+            OpenTarget::Missing => {
+                let path = self.canonical_file_path(resolved_path, false);
+                let already_open = self
+                    .buffers
+                    .values()
+                    .any(|state| state.buffer.file_path() == Some(path.as_path()));
+                if self.config.files.missing_files == MissingFileMode::Ask && !already_open {
+                    self.ask_to_create_file(path);
+                    Ok(None)
+                } else {
+                    self.open_file(&path).map(Some)
+                }
+            }
+            OpenTarget::Directory => {
+                let dir = resolved_path.canonicalize().unwrap_or(resolved_path);
+                self.show_directory_in_file_explorer(dir);
+                Ok(None)
+            }
+            OpenTarget::Special(kind) => {
+                tracing::warn!("Refusing to open {:?}: {}", resolved_path, kind.as_str());
+                self.set_status_message(
+                    t!(
+                        "file.special_file",
+                        path = resolved_path.display().to_string(),
+                        kind = kind.as_str()
+                    )
+                    .to_string(),
+                );
+                Ok(None)
+            }
+        }
+    }
+
+    /// Show the file explorer with `dir` expanded and selected
+    ///
+    /// A directory outside the explorer's root becomes its root.
+    fn show_directory_in_file_explorer(&mut self, dir: PathBuf) {
+        let root = self
+            .file_explorer
+            .as_ref()
+            .map(|view| view.tree().root_path().to_path_buf())
+            .unwrap_or_else(|| self.working_dir.clone());
+        if !dir.starts_with(&root) {
+            self.file_explorer_visible = true;
+            self.key_context = KeyContext::FileExplorer;
+            self.set_file_explorer_root(&dir);
+            return;
+        }
+
+        self.focus_file_explorer();
+        // Revealed now, or once the explorer is loaded or done syncing to
+        // the active file
+        self.pending_file_explorer_reveal = Some(dir);
+        if self.file_explorer.is_some() && !self.file_explorer_sync_in_progress {
+            self.reveal_pending_in_file_explorer();
+        }
+    }
+
+    /// Ask whether to open a buffer for the missing file at `path`
+    fn ask_to_create_file(&mut self, path: PathBuf) {
+        if self.prompt.is_some() {
+            // Asked once the current prompt is answered
+            if !self.missing_file_prompts.queue.contains(&path) {
+                self.missing_file_prompts.queue.push_back(path);
+            }
+            return;
+        }
+        self.start_prompt(
+            t!(
+                "prompt.create_missing_file",
+                path = path.display().to_string(),
+                create_key = t!("prompt.key.create"),
+                cancel_key = t!("prompt.key.cancel")
+            )
+            .to_string(),
+            PromptType::ConfirmCreateFile { path },
+        );
+    }
+
+    /// Answer to "file doesn't exist, create it?"
+    pub(super) fn handle_confirm_create_file(&mut self, input: &str, path: PathBuf) {
+        let input_lower = input.trim().to_lowercase();
+        let create_key = t!("prompt.key.create").to_string().to_lowercase();
+        if input_lower == create_key || input_lower == "create" {
+            if let Err(e) = self.open_file(&path) {
+                self.set_status_message(
+                    t!("file.error_opening", error = e.to_string()).to_string(),
+                );
+            }
+        } else {
+            self.set_status_message(
+                t!("file.create_cancelled", path = path.display().to_string()).to_string(),
+            );
+        }
+        if let Some(next) = self.missing_file_prompts.queue.pop_front() {
+            self.ask_to_create_file(next);
+        }
+    }
+
+    /// Forget the missing files still to be asked about, when the user
+    /// dismisses the prompt
+    pub(super) fn cancel_missing_file_prompts(&mut self) {
+        self.missing_file_prompts.queue.clear();
+    }
+}
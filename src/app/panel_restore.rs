@@ -0,0 +1,234 @@
+//! Plugin panels that survive editor restarts
+//!
+//! A plugin opts in per panel with `persistent: true` on
+//! `createVirtualBufferInSplit`. The session then records the panel's owning
+//! plugin, panel_id, name and mode, and its position in the split layout.
+//!
+//! On restore the editor recreates the split with a placeholder buffer and
+//! fires the `panel_restore` hook so the plugin can fill it again. If the
+//! plugin does not do so within [`PANEL_RESTORE_TIMEOUT`], the placeholder
+//! explains what happened. If the plugin is no longer loaded, the placeholder
+//! says so and can be closed with `q` or Enter.
+
+use super::Editor;
+use crate::input::buffer_mode::BufferMode;
+use crate::model::event::BufferId;
+use crate::primitives::text_property::TextPropertyEntry;
+use crate::services::plugins::hooks::HookArgs;
+use crate::session::SerializedPanel;
+use crossterm::event::{KeyCode, KeyModifiers};
+use rust_i18n::t;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// How long a restored panel waits for its plugin before explaining itself
+pub(crate) const PANEL_RESTORE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Mode of placeholders whose plugin is not loaded
+const ORPHANED_PANEL_MODE: &str = "orphaned-panel";
+
+/// A plugin panel that is saved with the session
+#[derive(Debug, Clone)]
+pub(crate) struct PersistentPanel {
+    /// Plugin that created the panel
+    pub plugin: String,
+    /// The plugin's panel_id
+    pub panel_id: String,
+    /// Set while a restored placeholder waits for the plugin to fill it
+    pub restore_deadline: Option<Instant>,
+}
+
+impl Editor {
+    /// Remember (or forget) a plugin panel for the session
+    ///
+    /// The latest `createVirtualBufferInSplit` call for a panel decides
+    /// whether it is persistent.
+    pub(super) fn register_persistent_panel(
+        &mut self,
+        buffer_id: BufferId,
+        persistent: bool,
+        plugin: Option<String>,
+        panel_id: &str,
+    ) {
+        match plugin {
+            Some(plugin) if persistent => {
+                self.persistent_panels.insert(
+                    buffer_id,
+                    PersistentPanel {
+                        plugin,
+                        panel_id: panel_id.to_string(),
+                        restore_deadline: None,
+                    },
+                );
+            }
+            _ => {
+                self.persistent_panels.remove(&buffer_id);
+            }
+        }
+    }
+
+    /// Persistent panels to save, and the index each panel buffer is saved under
+    pub(super) fn capture_panels(&self) -> (Vec<SerializedPanel>, HashMap<BufferId, usize>) {
+        let mut buffer_ids: Vec<BufferId> = self.persistent_panels.keys().copied().collect();
+        buffer_ids.sort_by_key(|id| id.0);
+
+        let mut panels = Vec::new();
+        let mut indices = HashMap::new();
+        for buffer_id in buffer_ids {
+            let Some(metadata) = self.buffer_metadata.get(&buffer_id) else {
+                continue;
+            };
+            let Some(mode) = metadata.virtual_mode() else {
+                continue;
+            };
+            let panel = &self.persistent_panels[&buffer_id];
+            let panel_index = panels.len();
+            indices.insert(buffer_id, panel_index);
+            panels.push(SerializedPanel {
+                panel_index,
+                plugin: panel.plugin.clone(),
+                panel_id: panel.panel_id.clone(),
+                name: metadata.display_name.clone(),
+                mode: mode.to_string(),
+                pinned: metadata.pinned,
+            });
+        }
+        (panels, indices)
+    }
+
+    /// Recreate a saved panel as a placeholder buffer
+    pub(super) fn restore_panel_from_session(&mut self, panel: &SerializedPanel) -> BufferId {
+        let plugin_loaded = self.is_plugin_loaded(&panel.plugin);
+        let (mode, text) = if plugin_loaded {
+            (
+                panel.mode.clone(),
+                t!("panel.restoring", plugin = &panel.plugin).to_string(),
+            )
+        } else {
+            self.mode_registry.register(
+                BufferMode::new(ORPHANED_PANEL_MODE)
+                    .with_read_only(true)
+                    .with_binding(KeyCode::Char('q'), KeyModifiers::NONE, "close")
+                    .with_binding(KeyCode::Enter, KeyModifiers::NONE, "close"),
+            );
+            (
+                ORPHANED_PANEL_MODE.to_string(),
+                t!(
+                    "panel.plugin_not_loaded",
+                    panel = &panel.panel_id,
+                    plugin = &panel.plugin
+                )
+                .to_string(),
+            )
+        };
+
+        let buffer_id = self.create_virtual_buffer(panel.name.clone(), mode, true);
+        if let Some(state) = self.buffers.get_mut(&buffer_id) {
+            state.margins.set_line_numbers(false);
+        }
+        self.set_panel_placeholder(buffer_id, text);
+        self.set_buffer_pinned(buffer_id, panel.pinned);
+
+        self.panel_ids.insert(panel.panel_id.clone(), buffer_id);
+        self.persistent_panels.insert(
+            buffer_id,
+            PersistentPanel {
+                plugin: panel.plugin.clone(),
+                panel_id: panel.panel_id.clone(),
+                restore_deadline: plugin_loaded
+                    .then(|| self.time_source.now() + PANEL_RESTORE_TIMEOUT),
+            },
+        );
+        buffer_id
+    }
+
+    /// Ask the owning plugins to fill the placeholders restored from the session
+    ///
+    /// Called once the split layout is rebuilt, so the hook can name the split.
+    pub(super) fn request_panel_restores(&mut self, buffer_ids: &[BufferId]) {
+        for &buffer_id in buffer_ids {
+            let Some(panel) = self.persistent_panels.get(&buffer_id) else {
+                continue;
+            };
+            if panel.restore_deadline.is_none() {
+                // The plugin is not loaded; nobody would answer
+                continue;
+            }
+            let split_id = self
+                .split_manager
+                .splits_for_buffer(buffer_id)
+                .first()
+                .copied();
+            tracing::debug!(
+                "Requesting restore of panel '{}' from plugin '{}'",
+                panel.panel_id,
+                panel.plugin
+            );
+            self.plugin_manager.run_hook(
+                "panel_restore",
+                HookArgs::PanelRestore {
+                    plugin: panel.plugin.clone(),
+                    panel_id: panel.panel_id.clone(),
+                    buffer_id,
+                    split_id,
+                },
+            );
+        }
+    }
+
+    /// Explain placeholders whose plugin did not fill them in time
+    ///
+    /// Returns true if a placeholder changed and needs a redraw.
+    pub(super) fn check_panel_restore_timeouts(&mut self) -> bool {
+        let now = self.time_source.now();
+        let expired: Vec<(BufferId, String)> = self
+            .persistent_panels
+            .iter()
+            .filter(|(_, panel)| panel.restore_deadline.is_some_and(|d| now >= d))
+            .map(|(&buffer_id, panel)| {
+                let text = t!(
+                    "panel.restore_timeout",
+                    panel = &panel.panel_id,
+                    plugin = &panel.plugin
+                )
+                .to_string();
+                (buffer_id, text)
+            })
+            .collect();
+
+        for (buffer_id, text) in &expired {
+            tracing::warn!(
+                "Restored panel {:?} was not filled by its plugin",
+                buffer_id
+            );
+            self.set_panel_placeholder(*buffer_id, text.clone());
+        }
+        !expired.is_empty()
+    }
+
+    /// Replace a panel's content with a placeholder message
+    fn set_panel_placeholder(&mut self, buffer_id: BufferId, text: String) {
+        let entry = TextPropertyEntry {
+            text: format!("{}\n", text),
+            properties: HashMap::new(),
+        };
+        if let Err(e) = self.set_virtual_buffer_content(buffer_id, vec![entry]) {
+            tracing::error!("Failed to set panel placeholder: {}", e);
+        }
+    }
+
+    fn is_plugin_loaded(&self, name: &str) -> bool {
+        #[cfg(feature = "plugins")]
+        {
+            self.plugin_manager
+                .list_plugins()
+                .iter()
+                .any(|plugin| plugin.name == name)
+        }
+        #[cfg(not(feature = "plugins"))]
+        {
+            let _ = name;
+            false
+        }
+    }
+}
@@ -0,0 +1,87 @@
+//! Changes on disk reported to plugins that watch paths or globs
+//!
+//! Plugins subscribe with `editor.watchPath(pathOrGlob, handler)`. The plugin
+//! runtime assigns the watch id and enforces the per-plugin limit; the editor
+//! polls the watches and runs the watch's own hook (see
+//! [`path_watch_hook_name`]) for every settled change.
+//!
+//! Watches belong to this editor instance: they end with `unwatchPath`, when
+//! their plugin is unloaded, or when the editor restarts in another directory.
+
+use super::Editor;
+use crate::services::path_watch::PathWatch;
+use crate::services::plugins::hooks::{path_watch_hook_name, HookArgs};
+use std::time::Duration;
+
+/// How often watched paths are checked
+pub(crate) const PATH_WATCH_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// A path or glob watched for a plugin
+pub(crate) struct PluginPathWatch {
+    /// Plugin that owns the watch
+    pub plugin: String,
+    pub watch: PathWatch,
+}
+
+impl Editor {
+    /// Start watching a path or glob for a plugin
+    pub(super) fn add_path_watch(&mut self, watch_id: u32, plugin: String, pattern: &str) {
+        match PathWatch::new(pattern, &self.working_dir) {
+            Ok(watch) => {
+                tracing::debug!(
+                    "Plugin '{}' watches '{}' (watch {})",
+                    plugin,
+                    pattern,
+                    watch_id
+                );
+                self.path_watches
+                    .insert(watch_id, PluginPathWatch { plugin, watch });
+            }
+            Err(e) => {
+                tracing::warn!("Plugin '{}' cannot watch '{}': {}", plugin, pattern, e);
+            }
+        }
+    }
+
+    /// Stop a plugin's path watch
+    pub(super) fn remove_path_watch(&mut self, watch_id: u32) {
+        if let Some(watch) = self.path_watches.remove(&watch_id) {
+            tracing::debug!("Plugin '{}' stopped watch {}", watch.plugin, watch_id);
+        }
+    }
+
+    /// Report settled changes of watched paths to their plugins
+    ///
+    /// Returns true if any change was reported.
+    pub(super) fn poll_path_watches(&mut self) -> bool {
+        if self.path_watches.is_empty()
+            || self.time_source.elapsed_since(self.last_path_watch_poll) < PATH_WATCH_POLL_INTERVAL
+        {
+            return false;
+        }
+        self.last_path_watch_poll = self.time_source.now();
+
+        let mut watch_ids: Vec<u32> = self.path_watches.keys().copied().collect();
+        watch_ids.sort_unstable();
+
+        let mut any_changed = false;
+        for watch_id in watch_ids {
+            let Some(path_watch) = self.path_watches.get_mut(&watch_id) else {
+                continue;
+            };
+            for change in path_watch.watch.poll() {
+                tracing::trace!("Watch {}: {:?}", watch_id, change);
+                self.plugin_manager.run_hook(
+                    &path_watch_hook_name(watch_id),
+                    HookArgs::PathChanged {
+                        watch_id,
+                        path: change.path,
+                        kind: change.kind,
+                    },
+                );
+                any_changed = true;
+            }
+        }
+        any_changed
+    }
+}
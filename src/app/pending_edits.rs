@@ -0,0 +1,811 @@
+//! Pending edits review buffer
+//!
+//! Shows a `PendingEditSet` in a read-only virtual buffer where the user can
+//! toggle individual edits or whole files, expand context around an edit, and
+//! then apply the checked edits. Each file's edits go through the same per-buffer
+//! bulk edit path as LSP workspace edits, so one undo reverts everything the
+//! review changed in that buffer.
+
+use crate::model::event::{BufferId, CursorId, Event};
+use crate::model::pending_edits::{PendingEdit, PendingEditSet};
+use crate::primitives::text_property::TextPropertyEntry;
+use crate::state::EditorState;
+use crate::view::glyphs::glyphs;
+use rust_i18n::t;
+use serde_json::json;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use super::Editor;
+
+/// Display name of the review buffer
+pub(crate) const PENDING_EDITS_BUFFER_NAME: &str = "*Pending Edits*";
+
+/// Buffer mode of the review buffer (bindings live in `ModeRegistry::new`)
+pub(crate) const PENDING_EDITS_MODE: &str = "pending-edits";
+
+/// Number of unchanged lines shown above and below an expanded edit
+const CONTEXT_LINES: usize = 2;
+
+/// Maximum width of the inline old/new text summary
+const SUMMARY_WIDTH: usize = 40;
+
+/// Location and surrounding text of one edit, captured when the review opens
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct EditPreview {
+    /// 1-based line of the edit start (0 if the file could not be read)
+    pub line: usize,
+    /// 1-based column (in characters) of the edit start
+    pub column: usize,
+    /// Unchanged lines before the edit, with their line numbers
+    pub before: Vec<(usize, String)>,
+    /// Lines touched by the edit as they are now
+    pub removed: Vec<String>,
+    /// The same lines with the edit applied
+    pub added: Vec<String>,
+    /// Unchanged lines after the edit, with their line numbers
+    pub after: Vec<(usize, String)>,
+}
+
+impl EditPreview {
+    /// Build a preview from the current file content
+    ///
+    /// Returns an empty preview if the range does not fit the content.
+    pub fn from_content(content: &str, edit: &PendingEdit) -> Self {
+        let (start, end) = (edit.range.start, edit.range.end);
+        if start > end
+            || end > content.len()
+            || !content.is_char_boundary(start)
+            || !content.is_char_boundary(end)
+        {
+            return Self::default();
+        }
+
+        let line_start = content[..start].rfind('\n').map_or(0, |i| i + 1);
+        let line_end = content[end..].find('\n').map_or(content.len(), |i| end + i);
+        let line = content[..start].matches('\n').count() + 1;
+        let column = content[line_start..start].chars().count() + 1;
+
+        let replaced = format!(
+            "{}{}{}",
+            &content[line_start..start],
+            edit.new_text,
+            &content[end..line_end]
+        );
+
+        let before_lines: Vec<&str> = content[..line_start].lines().collect();
+        let skip = before_lines.len().saturating_sub(CONTEXT_LINES);
+        let before = before_lines[skip..]
+            .iter()
+            .enumerate()
+            .map(|(i, text)| (skip + i + 1, text.to_string()))
+            .collect();
+
+        let removed: Vec<String> = content[line_start..line_end]
+            .split('\n')
+            .map(str::to_string)
+            .collect();
+        let first_after = line + removed.len();
+        let after_text = content
+            .get(line_end + 1..)
+            .filter(|_| line_end < content.len())
+            .unwrap_or("");
+        let after = after_text
+            .lines()
+            .take(CONTEXT_LINES)
+            .enumerate()
+            .map(|(i, text)| (first_after + i, text.to_string()))
+            .collect();
+
+        Self {
+            line,
+            column,
+            before,
+            removed,
+            added: replaced.split('\n').map(str::to_string).collect(),
+            after,
+        }
+    }
+}
+
+/// An open pending edits review
+pub(crate) struct PendingEditsReview {
+    pub set: PendingEditSet,
+    /// Preview for each edit, indexed like `set.edits()`
+    pub previews: Vec<EditPreview>,
+    /// Indices of edits whose context is expanded
+    pub expanded: HashSet<usize>,
+    pub title: String,
+    pub buffer_id: BufferId,
+    /// Plugin request waiting for the result (None if started by the editor)
+    pub request_id: Option<u64>,
+    /// What confirming does with the checked edits
+    pub on_confirm: OnConfirm,
+}
+
+/// What confirming a review does with the checked edits
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum OnConfirm {
+    /// Only report the selection to the plugin that asked
+    Report,
+    /// Apply the edits, skipping any whose text changed
+    Apply,
+    /// Apply the edits like `Apply`, then save each file whose buffer had no
+    /// unsaved edits, closing the buffers opened only for this
+    ApplyAndSave,
+    /// Apply the edits as an LSP rename: all of them or none
+    Rename,
+}
+
+impl PendingEditsReview {
+    /// Build the review buffer content
+    fn entries(&self, working_dir: &Path) -> Vec<TextPropertyEntry> {
+        let mut entries = Vec::new();
+        let counts = self.set.counts();
+
+        entries.push(
+            TextPropertyEntry::text(format!(
+                "{}\n",
+                t!("pending_edits.title", title = &self.title)
+            ))
+            .with_property("type", json!("header")),
+        );
+        entries.push(
+            TextPropertyEntry::text(format!(
+                "{}\n",
+                t!(
+                    "pending_edits.counts",
+                    edits = counts.enabled_edits,
+                    total_edits = counts.total_edits,
+                    files = counts.enabled_files,
+                    total_files = counts.total_files
+                )
+            ))
+            .with_property("type", json!("header")),
+        );
+        entries.push(
+            TextPropertyEntry::text(format!("{}\n\n", t!("pending_edits.help")))
+                .with_property("type", json!("help")),
+        );
+
+        if self.set.is_empty() {
+            entries.push(TextPropertyEntry::text(format!(
+                "  {}\n",
+                t!("pending_edits.empty")
+            )));
+            return entries;
+        }
+
+        for (file, indices) in self.set.files() {
+            let (enabled, total) = self.set.file_counts(file);
+            let checkbox = match enabled {
+                0 => "[ ]",
+                n if n == total => "[x]",
+                _ => "[-]",
+            };
+            let display = file
+                .strip_prefix(working_dir)
+                .unwrap_or(file)
+                .display()
+                .to_string();
+            entries.push(
+                TextPropertyEntry::text(format!(
+                    "{} {}  ({}/{})\n",
+                    checkbox, display, enabled, total
+                ))
+                .with_property("type", json!("file"))
+                .with_property("file", json!(file.to_string_lossy())),
+            );
+
+            for &index in indices {
+                let edit = &self.set.edits()[index];
+                let preview = &self.previews[index];
+                let checkbox = if edit.enabled { "[x]" } else { "[ ]" };
+                let location = if preview.line > 0 {
+                    format!("{}:{}", preview.line, preview.column)
+                } else {
+                    format!("@{}", edit.range.start)
+                };
+                let marker = if self.expanded.contains(&index) {
+                    glyphs().expanded
+                } else {
+                    glyphs().collapsed
+                };
+                entries.push(
+                    TextPropertyEntry::text(format!(
+                        "    {} {} {:<8} {} {} {}\n",
+                        marker,
+                        checkbox,
+                        location,
+                        summarize(&edit.old_text),
+                        glyphs().maps_to,
+                        summarize(&edit.new_text)
+                    ))
+                    .with_property("type", json!("edit"))
+                    .with_property("index", json!(index)),
+                );
+
+                if self.expanded.contains(&index) {
+                    for text in preview_lines(preview) {
+                        entries.push(
+                            TextPropertyEntry::text(text)
+                                .with_property("type", json!("context"))
+                                .with_property("index", json!(index)),
+                        );
+                    }
+                }
+            }
+        }
+
+        entries
+    }
+}
+
+/// The edits (ordered by position) whose range still holds their `old_text`
+/// and doesn't overlap an earlier edit
+pub(super) fn valid_pending_edits(
+    state: &mut EditorState,
+    edits: Vec<PendingEdit>,
+) -> Vec<PendingEdit> {
+    let mut valid: Vec<PendingEdit> = Vec::new();
+    let mut last_end = 0;
+    for edit in edits {
+        let range = edit.range.clone();
+        let fits = range.start <= range.end
+            && range.end <= state.buffer.len()
+            && (valid.is_empty() || range.start >= last_end);
+        if !fits || state.get_text_range(range.start, range.end) != edit.old_text {
+            continue;
+        }
+        last_end = range.end;
+        valid.push(edit);
+    }
+    valid
+}
+
+/// Delete/insert events for edits ordered by position
+pub(super) fn pending_edit_events(edits: &[PendingEdit], cursor_id: CursorId) -> Vec<Event> {
+    // Emit events from the last edit backwards so that an insertion sharing a
+    // position with the following edit ends up before it
+    let mut events = Vec::with_capacity(edits.len() * 2);
+    for edit in edits.iter().rev() {
+        if !edit.range.is_empty() {
+            events.push(Event::Delete {
+                range: edit.range.clone(),
+                deleted_text: edit.old_text.clone(),
+                cursor_id,
+            });
+        }
+        if !edit.new_text.is_empty() {
+            events.push(Event::Insert {
+                position: edit.range.start,
+                text: edit.new_text.clone(),
+                cursor_id,
+            });
+        }
+    }
+    events
+}
+
+/// Single-line, width-limited rendering of edit text
+fn summarize(text: &str) -> String {
+    let flat = text.replace('\n', "⏎").replace('\t', " ");
+    if flat.is_empty() {
+        return "∅".to_string();
+    }
+    if flat.chars().count() > SUMMARY_WIDTH {
+        let truncated: String = flat.chars().take(SUMMARY_WIDTH - 1).collect();
+        format!("{}{}", truncated, glyphs().ellipsis)
+    } else {
+        flat
+    }
+}
+
+/// Lines shown under an expanded edit: context, then removed (-) and added (+) lines
+fn preview_lines(preview: &EditPreview) -> Vec<String> {
+    if preview.line == 0 {
+        return vec![format!("          {}\n", t!("pending_edits.no_preview"))];
+    }
+    let mut lines = Vec::new();
+    for (line, text) in &preview.before {
+        lines.push(format!("        {:>5}   {}\n", line, text));
+    }
+    for (i, text) in preview.removed.iter().enumerate() {
+        lines.push(format!("        {:>5} - {}\n", preview.line + i, text));
+    }
+    for (i, text) in preview.added.iter().enumerate() {
+        lines.push(format!("        {:>5} + {}\n", preview.line + i, text));
+    }
+    for (line, text) in &preview.after {
+        lines.push(format!("        {:>5}   {}\n", line, text));
+    }
+    lines
+}
+
+impl Editor {
+    /// Open the pending edits review buffer for a set of proposed edits
+    ///
+    /// When the user confirms, the checked edits are applied to their buffers
+    /// (opening files as needed), one bulk edit per buffer.
+    pub fn review_pending_edits(&mut self, title: String, edits: Vec<PendingEdit>) {
+        self.start_pending_edits_review(title, edits, None, OnConfirm::Apply);
+    }
+
+    /// Open the review buffer, optionally on behalf of a plugin request
+    pub(super) fn start_pending_edits_review(
+        &mut self,
+        title: String,
+        edits: Vec<PendingEdit>,
+        request_id: Option<u64>,
+        on_confirm: OnConfirm,
+    ) {
+        // Only one review at a time: a new review cancels the previous one
+        if self.pending_edits_review.is_some() {
+            self.finish_pending_edits_review(None);
+        }
+
+        let set = PendingEditSet::new(edits);
+        let previews = self.pending_edit_previews(&set);
+
+        let buffer_id = self.create_virtual_buffer(
+            PENDING_EDITS_BUFFER_NAME.to_string(),
+            PENDING_EDITS_MODE.to_string(),
+            true,
+        );
+        if let Some(state) = self.buffers.get_mut(&buffer_id) {
+            state.editing_disabled = true;
+            state.margins.set_line_numbers(false);
+        }
+
+        self.pending_edits_review = Some(PendingEditsReview {
+            set,
+            previews,
+            expanded: HashSet::new(),
+            title,
+            buffer_id,
+            request_id,
+            on_confirm,
+        });
+        self.refresh_pending_edits_review();
+        self.set_active_buffer(buffer_id);
+
+        // Start on the first edit rather than the header
+        let first_edit = self.buffers.get(&buffer_id).and_then(|state| {
+            state
+                .text_properties
+                .all()
+                .iter()
+                .find(|p| p.get("type") == Some(&json!("edit")))
+                .map(|p| p.start)
+        });
+        if let Some(position) = first_edit {
+            if let Some(state) = self.buffers.get_mut(&buffer_id) {
+                state.cursors.primary_mut().position = position;
+            }
+        }
+    }
+
+    /// Capture a preview for every edit, reading each file once
+    fn pending_edit_previews(&self, set: &PendingEditSet) -> Vec<EditPreview> {
+        let mut previews = vec![EditPreview::default(); set.edits().len()];
+        for (file, indices) in set.files() {
+            let path = self.resolve_pending_edit_path(file);
+            let open_text = self
+                .buffer_metadata
+                .iter()
+                .find(|(_, m)| m.file_path() == Some(&path))
+                .and_then(|(id, _)| self.buffers.get(id))
+                .and_then(|state| state.buffer.to_string());
+            let Some(content) = open_text.or_else(|| std::fs::read_to_string(&path).ok()) else {
+                continue;
+            };
+            for &index in indices {
+                previews[index] = EditPreview::from_content(&content, &set.edits()[index]);
+            }
+        }
+        previews
+    }
+
+    /// Resolve an edit's file against the working directory, matching how buffers store paths
+    pub(super) fn resolve_pending_edit_path(&self, file: &Path) -> PathBuf {
+        let path = if file.is_relative() {
+            self.working_dir.join(file)
+        } else {
+            file.to_path_buf()
+        };
+        path.canonicalize().unwrap_or(path)
+    }
+
+    /// Re-render the review buffer from the current review state
+    fn refresh_pending_edits_review(&mut self) {
+        let Some(review) = &self.pending_edits_review else {
+            return;
+        };
+        let buffer_id = review.buffer_id;
+        let entries = review.entries(&self.working_dir);
+        if let Err(e) = self.set_virtual_buffer_content(buffer_id, entries) {
+            tracing::error!("Failed to render pending edits review: {}", e);
+        }
+    }
+
+    /// Text property `type` and value under the cursor in the review buffer
+    fn pending_edits_item_at_cursor(&self) -> Option<(String, serde_json::Value)> {
+        let review = self.pending_edits_review.as_ref()?;
+        if self.active_buffer() != review.buffer_id {
+            return None;
+        }
+        self.get_text_properties_at_cursor()?
+            .into_iter()
+            .find_map(|p| {
+                let kind = p.get("type")?.as_str()?.to_string();
+                let value = match kind.as_str() {
+                    "file" => p.get("file")?.clone(),
+                    "edit" | "context" => p.get("index")?.clone(),
+                    _ => return None,
+                };
+                Some((kind, value))
+            })
+    }
+
+    /// Show the enabled/total counts in the status bar
+    fn set_pending_edits_status(&mut self) {
+        if let Some(review) = &self.pending_edits_review {
+            let counts = review.set.counts();
+            self.set_status_message(
+                t!(
+                    "pending_edits.counts",
+                    edits = counts.enabled_edits,
+                    total_edits = counts.total_edits,
+                    files = counts.enabled_files,
+                    total_files = counts.total_files
+                )
+                .to_string(),
+            );
+        }
+    }
+
+    /// Toggle the edit under the cursor, or the whole file on a file header
+    pub(super) fn pending_edits_toggle(&mut self) {
+        let Some((kind, value)) = self.pending_edits_item_at_cursor() else {
+            return;
+        };
+        let Some(review) = self.pending_edits_review.as_mut() else {
+            return;
+        };
+        match kind.as_str() {
+            "file" => {
+                if let Some(file) = value.as_str() {
+                    review.set.toggle_file(Path::new(file));
+                }
+            }
+            _ => {
+                if let Some(index) = value.as_u64() {
+                    review.set.toggle(index as usize);
+                }
+            }
+        }
+        self.refresh_pending_edits_review();
+        self.set_pending_edits_status();
+    }
+
+    /// Include or exclude every edit in the file under the cursor
+    pub(super) fn pending_edits_toggle_file(&mut self) {
+        let Some((kind, value)) = self.pending_edits_item_at_cursor() else {
+            return;
+        };
+        let Some(review) = self.pending_edits_review.as_mut() else {
+            return;
+        };
+        let file = match kind.as_str() {
+            "file" => value.as_str().map(PathBuf::from),
+            _ => value
+                .as_u64()
+                .and_then(|i| review.set.edits().get(i as usize))
+                .map(|e| e.file.clone()),
+        };
+        if let Some(file) = file {
+            review.set.toggle_file(&file);
+        }
+        self.refresh_pending_edits_review();
+        self.set_pending_edits_status();
+    }
+
+    /// Expand or collapse the context preview of the edit under the cursor
+    pub(super) fn pending_edits_toggle_expand(&mut self) {
+        let Some((kind, value)) = self.pending_edits_item_at_cursor() else {
+            return;
+        };
+        if kind == "file" {
+            return;
+        }
+        let Some(index) = value.as_u64().map(|i| i as usize) else {
+            return;
+        };
+        let Some(review) = self.pending_edits_review.as_mut() else {
+            return;
+        };
+        if !review.expanded.remove(&index) {
+            review.expanded.insert(index);
+        }
+        self.refresh_pending_edits_review();
+
+        // Collapsing from a context line would leave the cursor on an unrelated row;
+        // move it back to the edit's own line
+        if kind == "context" {
+            let buffer_id = self.active_buffer();
+            if let Some(state) = self.buffers.get_mut(&buffer_id) {
+                let position = state
+                    .text_properties
+                    .all()
+                    .iter()
+                    .find(|p| {
+                        p.get("type") == Some(&json!("edit"))
+                            && p.get("index") == Some(&json!(index))
+                    })
+                    .map(|p| p.start);
+                if let Some(position) = position {
+                    state.cursors.primary_mut().position = position;
+                }
+            }
+        }
+    }
+
+    /// Apply the checked edits and close the review
+    ///
+    /// As a dry run, reports the edits per file and keeps the review open.
+    pub(super) fn pending_edits_apply(&mut self) {
+        let Some(review) = &self.pending_edits_review else {
+            self.set_status_message(t!("pending_edits.no_review").to_string());
+            return;
+        };
+        let enabled_edits = review.set.counts().enabled_edits;
+        if enabled_edits == 0 {
+            self.set_status_message(t!("pending_edits.none_selected").to_string());
+            return;
+        }
+        let selected = review.set.selected();
+        let groups = review.set.selected_by_file();
+        if self.dry_run {
+            let summary = t!(
+                "dry_run.apply_pending_edits",
+                count = enabled_edits,
+                files = groups.len()
+            )
+            .to_string();
+            let items = groups
+                .iter()
+                .map(|(file, edits)| {
+                    let path = self.resolve_pending_edit_path(file);
+                    let shown = path.strip_prefix(&self.working_dir).unwrap_or(&path);
+                    t!(
+                        "dry_run.file_edits",
+                        file = shown.display().to_string(),
+                        count = edits.len()
+                    )
+                    .to_string()
+                })
+                .collect();
+            self.report_dry_run(summary, items);
+            return;
+        }
+        let on_confirm = review.on_confirm;
+        self.finish_pending_edits_review(Some(selected));
+        match on_confirm {
+            OnConfirm::Report => {}
+            OnConfirm::Apply => self.apply_pending_edit_groups(groups, false),
+            OnConfirm::ApplyAndSave => self.apply_pending_edit_groups(groups, true),
+            OnConfirm::Rename => self.apply_rename_edits(groups),
+        }
+    }
+
+    /// Close the review without applying anything
+    pub(super) fn pending_edits_cancel(&mut self) {
+        if self.pending_edits_review.is_some() {
+            self.finish_pending_edits_review(None);
+            self.set_status_message(t!("pending_edits.cancelled").to_string());
+        }
+    }
+
+    /// Close the review buffer and answer the plugin request, if any
+    fn finish_pending_edits_review(&mut self, result: Option<Vec<PendingEdit>>) {
+        let Some(review) = self.pending_edits_review.take() else {
+            return;
+        };
+        if self.buffers.contains_key(&review.buffer_id) {
+            if let Err(e) = self.close_buffer(review.buffer_id) {
+                tracing::warn!("Failed to close pending edits review buffer: {}", e);
+            }
+        }
+        self.respond_pending_edits_review(&review, result);
+    }
+
+    /// Treat closing the review buffer by other means as a cancel
+    pub(super) fn on_pending_edits_buffer_closed(&mut self, buffer_id: BufferId) {
+        if self
+            .pending_edits_review
+            .as_ref()
+            .is_some_and(|r| r.buffer_id == buffer_id)
+        {
+            if let Some(review) = self.pending_edits_review.take() {
+                self.respond_pending_edits_review(&review, None);
+            }
+        }
+    }
+
+    fn respond_pending_edits_review(
+        &mut self,
+        review: &PendingEditsReview,
+        result: Option<Vec<PendingEdit>>,
+    ) {
+        if let Some(request_id) = review.request_id {
+            self.send_plugin_response(
+                crate::services::plugins::api::PluginResponse::PendingEditsReviewed {
+                    request_id,
+                    edits: result,
+                },
+            );
+        }
+    }
+
+    /// Apply grouped edits, one bulk edit per buffer
+    ///
+    /// Edits whose range no longer holds `old_text`, or that overlap an earlier
+    /// edit in the same file, are skipped. With `save`, each edited file is
+    /// saved unless its buffer already had unsaved edits, and buffers opened
+    /// for the edits are closed again.
+    fn apply_pending_edit_groups(&mut self, groups: Vec<(PathBuf, Vec<PendingEdit>)>, save: bool) {
+        let mut applied = 0;
+        let mut files = 0;
+        let mut skipped = 0;
+        let mut saved = 0;
+        let mut unsaved = 0;
+
+        for (file, edits) in groups {
+            let path = self.resolve_pending_edit_path(&file);
+            let open_before = self
+                .buffer_metadata
+                .iter()
+                .find(|(_, m)| m.file_path() == Some(&path))
+                .and_then(|(id, _)| self.buffers.get(id))
+                .map(|state| state.buffer.is_modified());
+            let buffer_id = match self.open_file_no_focus(&path) {
+                Ok(id) => id,
+                Err(e) => {
+                    tracing::warn!("Failed to open {:?} for pending edits: {}", path, e);
+                    skipped += edits.len();
+                    continue;
+                }
+            };
+            let Some(state) = self.buffers.get_mut(&buffer_id) else {
+                skipped += edits.len();
+                continue;
+            };
+
+            let total = edits.len();
+            let valid = valid_pending_edits(state, edits);
+            skipped += total - valid.len();
+            if valid.is_empty() {
+                continue;
+            }
+            let events = pending_edit_events(&valid, state.cursors.primary_id());
+
+            let description = format!("Apply {} reviewed edits", valid.len());
+            let label = t!("undo.reviewed_edits", count = valid.len()).to_string();
+            if let Err(e) = self.apply_events_to_buffer_as_bulk_edit(
+                buffer_id,
+                events,
+                description,
+                Some(label),
+            ) {
+                tracing::warn!("Failed to apply pending edits to {:?}: {}", path, e);
+                skipped += valid.len();
+                continue;
+            }
+            applied += valid.len();
+            files += 1;
+
+            if !save {
+                continue;
+            }
+            if open_before == Some(true) {
+                unsaved += 1;
+                continue;
+            }
+            match self.save_buffer(buffer_id) {
+                Ok(()) => saved += 1,
+                Err(e) => {
+                    tracing::warn!("Failed to save {:?} after pending edits: {}", path, e);
+                    unsaved += 1;
+                    continue;
+                }
+            }
+            if open_before.is_none() {
+                if let Err(e) = self.close_buffer(buffer_id) {
+                    tracing::debug!("Keeping {:?} open after pending edits: {}", path, e);
+                }
+            }
+        }
+
+        let message = if skipped > 0 {
+            t!(
+                "pending_edits.applied_with_skipped",
+                count = applied,
+                files = files,
+                skipped = skipped
+            )
+        } else {
+            t!("pending_edits.applied", count = applied, files = files)
+        };
+        let message = match (save, unsaved) {
+            (false, _) => message.to_string(),
+            (true, 0) => t!("pending_edits.saved", message = message, saved = saved).to_string(),
+            (true, _) => t!(
+                "pending_edits.saved_some",
+                message = message,
+                saved = saved,
+                unsaved = unsaved
+            )
+            .to_string(),
+        };
+        self.set_status_message(message);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn edit(range: std::ops::Range<usize>, old_text: &str, new_text: &str) -> PendingEdit {
+        PendingEdit {
+            file: PathBuf::from("a.txt"),
+            range,
+            old_text: old_text.to_string(),
+            new_text: new_text.to_string(),
+            enabled: true,
+        }
+    }
+
+    #[test]
+    fn test_preview_single_line_edit() {
+        let content = "one\ntwo\nthree foo\nfour\nfive\nsix\n";
+        let start = content.find("foo").unwrap();
+        let preview = EditPreview::from_content(content, &edit(start..start + 3, "foo", "bar"));
+
+        assert_eq!((preview.line, preview.column), (3, 7));
+        assert_eq!(
+            preview.before,
+            vec![(1, "one".to_string()), (2, "two".to_string())]
+        );
+        assert_eq!(preview.removed, vec!["three foo"]);
+        assert_eq!(preview.added, vec!["three bar"]);
+        assert_eq!(
+            preview.after,
+            vec![(4, "four".to_string()), (5, "five".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_preview_multi_line_edit_at_end() {
+        let content = "a\nb\nc";
+        let preview = EditPreview::from_content(content, &edit(2..5, "b\nc", "x"));
+
+        assert_eq!(preview.line, 2);
+        assert_eq!(preview.removed, vec!["b", "c"]);
+        assert_eq!(preview.added, vec!["x"]);
+        assert!(preview.after.is_empty());
+    }
+
+    #[test]
+    fn test_preview_out_of_range_is_empty() {
+        let preview = EditPreview::from_content("short", &edit(3..20, "x", "y"));
+        assert_eq!(preview, EditPreview::default());
+    }
+
+    #[test]
+    fn test_summarize_flattens_and_truncates() {
+        assert_eq!(summarize("a\nb"), "a⏎b");
+        assert_eq!(summarize(""), "∅");
+        let long = "x".repeat(SUMMARY_WIDTH + 5);
+        assert_eq!(summarize(&long).chars().count(), SUMMARY_WIDTH);
+    }
+}
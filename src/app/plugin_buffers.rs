@@ -0,0 +1,185 @@
+//! Buffers created by plugins, and closing the ones left behind
+//!
+//! A plugin that recreates its results buffer on every refresh without a
+//! `panel_id`, or never closes a panel, leaves buffers behind that no split
+//! shows. Each buffer a plugin creates remembers when it was last used:
+//! shown in a split or named by a command from a plugin. Those hidden and
+//! unused for `editor.plugin_buffer_idle_close_secs` are closed.
+//!
+//! A plugin that hides a buffer on purpose, to show it again later, keeps it
+//! with `setBufferIdleClose(bufferId, false)`.
+
+use super::Editor;
+use crate::model::event::BufferId;
+use std::time::{Duration, Instant};
+
+/// A buffer a plugin created
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct PluginBuffer {
+    /// When a split last showed the buffer or a plugin last named it
+    pub last_used: Instant,
+    /// Whether the buffer is closed once hidden and unused for long enough
+    pub idle_close: bool,
+}
+
+/// Sizes of the per-buffer stores, for finding leaks
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BufferStoreStats {
+    /// Open buffers
+    pub buffers: usize,
+    /// Entries of the editor's maps keyed by buffer (undo logs, metadata,
+    /// panels, plugin buffers)
+    pub buffer_entries: usize,
+    /// Overlays, virtual texts, line indicators and margin annotations of
+    /// all buffers
+    pub decorations: usize,
+    /// Markers of all buffers
+    pub markers: usize,
+    /// Entries of the per-buffer maps of the plugin state snapshot
+    pub snapshot_entries: usize,
+}
+
+impl Editor {
+    /// Track a buffer a plugin just created
+    pub(super) fn register_plugin_buffer(&mut self, buffer_id: BufferId) {
+        let now = self.time_source.now();
+        self.plugin_buffers.insert(
+            buffer_id,
+            PluginBuffer {
+                last_used: now,
+                idle_close: true,
+            },
+        );
+    }
+
+    /// Note that a plugin used one of its buffers
+    pub(super) fn touch_plugin_buffer(&mut self, buffer_id: BufferId) {
+        let now = self.time_source.now();
+        if let Some(buffer) = self.plugin_buffers.get_mut(&buffer_id) {
+            buffer.last_used = now;
+        }
+    }
+
+    /// Let a plugin keep a buffer it hides on purpose, or not
+    pub(super) fn set_plugin_buffer_idle_close(&mut self, buffer_id: BufferId, enabled: bool) {
+        match self.plugin_buffers.get_mut(&buffer_id) {
+            Some(buffer) => buffer.idle_close = enabled,
+            None => tracing::warn!(
+                "setBufferIdleClose: buffer {:?} was not created by a plugin",
+                buffer_id
+            ),
+        }
+    }
+
+    /// Close plugin buffers no split has shown and no plugin has used for
+    /// `editor.plugin_buffer_idle_close_secs`, unless pinned
+    ///
+    /// Returns true if any was closed and the screen needs a redraw.
+    pub fn close_idle_plugin_buffers(&mut self) -> bool {
+        let idle_secs = self.config.editor.plugin_buffer_idle_close_secs;
+        if idle_secs == 0 || self.plugin_buffers.is_empty() {
+            return false;
+        }
+        let now = self.time_source.now();
+        let idle = Duration::from_secs(idle_secs);
+        let active = self.active_buffer();
+
+        let mut expired = Vec::new();
+        for (&buffer_id, buffer) in self.plugin_buffers.iter_mut() {
+            // A tab a split isn't showing doesn't count
+            let shown =
+                buffer_id == active || !self.split_manager.splits_for_buffer(buffer_id).is_empty();
+            let pinned = self
+                .buffer_metadata
+                .get(&buffer_id)
+                .is_some_and(|metadata| metadata.pinned);
+            if shown {
+                buffer.last_used = now;
+            } else if buffer.idle_close && !pinned && now.duration_since(buffer.last_used) >= idle {
+                expired.push(buffer_id);
+            }
+        }
+
+        let mut closed = false;
+        for buffer_id in expired {
+            // Edits the user made to it are kept
+            match self.close_buffer(buffer_id) {
+                Ok(()) => {
+                    tracing::info!("Closed idle plugin buffer {:?}", buffer_id);
+                    closed = true;
+                }
+                Err(e) => {
+                    tracing::debug!("Keeping idle plugin buffer {:?}: {}", buffer_id, e);
+                    self.touch_plugin_buffer(buffer_id);
+                }
+            }
+        }
+        closed
+    }
+
+    /// Sizes of the per-buffer stores
+    pub fn buffer_store_stats(&self) -> BufferStoreStats {
+        let decorations = self
+            .buffers
+            .values()
+            .map(|state| {
+                state.overlays.len()
+                    + state.virtual_texts.len()
+                    + state.margins.line_indicator_count()
+                    + state
+                        .margins
+                        .annotation_count(crate::view::margin::MarginPosition::Left)
+                    + state
+                        .margins
+                        .annotation_count(crate::view::margin::MarginPosition::Right)
+            })
+            .sum();
+        let markers = self
+            .buffers
+            .values()
+            .map(|state| state.marker_list.marker_count())
+            .sum();
+
+        #[cfg(feature = "plugins")]
+        let snapshot_entries = self
+            .plugin_manager
+            .state_snapshot_handle()
+            .map_or(0, |handle| {
+                let snapshot = handle.read().unwrap();
+                snapshot.buffers.len()
+                    + snapshot.buffer_saved_diffs.len()
+                    + snapshot.buffer_cursor_positions.len()
+                    + snapshot.buffer_text_properties.len()
+            });
+        #[cfg(not(feature = "plugins"))]
+        let snapshot_entries = 0;
+
+        BufferStoreStats {
+            buffers: self.buffers.len(),
+            buffer_entries: self.event_logs.len()
+                + self.buffer_metadata.len()
+                + self.seen_byte_ranges.len()
+                + self.panel_ids.len()
+                + self.persistent_panels.len()
+                + self.ansi_entry_caches.len()
+                + self.plugin_buffers.len(),
+            decorations,
+            markers,
+            snapshot_entries,
+        }
+    }
+
+    /// Forget a closed buffer, dropping it from the plugin state snapshot
+    /// right away rather than at the next refresh
+    pub(super) fn on_plugin_buffer_closed(&mut self, buffer_id: BufferId) {
+        self.plugin_buffers.remove(&buffer_id);
+        #[cfg(feature = "plugins")]
+        if let Some(handle) = self.plugin_manager.state_snapshot_handle() {
+            let mut snapshot = handle.write().unwrap();
+            snapshot.buffers.remove(&buffer_id);
+            snapshot.buffer_saved_diffs.remove(&buffer_id);
+            snapshot.buffer_cursor_positions.remove(&buffer_id);
+            snapshot.buffer_text_properties.remove(&buffer_id);
+        }
+    }
+}
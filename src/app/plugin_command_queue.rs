@@ -0,0 +1,331 @@
+//! Plugin commands that arrive before their buffer or split exists
+//!
+//! Plugins decorate a buffer as soon as they learn its id, typically right
+//! after `createVirtualBufferInSplit` resolves. A command naming a buffer or
+//! split that doesn't exist, but whose id was handed out within the last
+//! [`RETRY_TICKS`] ticks (or is about to be), waits here and is retried each
+//! tick instead of being dropped. Once a command for a target is waiting,
+//! later commands for the same target queue behind it, so they still run in
+//! the order the plugin sent them. Commands still waiting after the window
+//! are dropped with one warning per target.
+//!
+//! Each tick runs the waiting commands first, then the new ones in the order
+//! they were sent. Responses to commands (such as the ids of a new virtual
+//! buffer) are delivered after the whole batch, so a plugin's follow-up
+//! commands always arrive in a later tick, after everything it sent before.
+
+use crate::services::plugins::api::{PluginCommand, PluginCommandTarget, PluginResponse};
+use std::collections::VecDeque;
+
+use super::Editor;
+
+/// Ticks a command waits for its buffer or split before it is dropped
+pub(crate) const RETRY_TICKS: u64 = 20;
+
+/// A command waiting for its target
+struct Waiting {
+    command: PluginCommand,
+    name: &'static str,
+    target: PluginCommandTarget,
+    /// Tick the command first arrived in
+    since: u64,
+}
+
+/// Commands dropped because their target never appeared
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) struct Expired {
+    /// Name of the first dropped command
+    pub name: &'static str,
+    pub target: PluginCommandTarget,
+    pub count: usize,
+}
+
+/// Commands waiting for their target, and responses held until the end of a
+/// batch
+#[derive(Default)]
+pub(crate) struct PluginCommandQueue {
+    tick: u64,
+    /// Next buffer and split ids at the start of each of the last
+    /// [`RETRY_TICKS`] ticks, oldest first
+    next_ids: VecDeque<(usize, usize)>,
+    waiting: Vec<Waiting>,
+    /// Responses of the batch being processed; None between batches
+    responses: Option<Vec<PluginResponse>>,
+}
+
+impl PluginCommandQueue {
+    /// Start a tick, given the ids the next buffer and split will get
+    ///
+    /// Returns the commands still waiting, in order, and drops the targets
+    /// whose oldest command has waited for the whole window.
+    fn start_tick(
+        &mut self,
+        next_buffer_id: usize,
+        next_split_id: usize,
+    ) -> (Vec<Waiting>, Vec<Expired>) {
+        self.tick += 1;
+        self.next_ids.push_back((next_buffer_id, next_split_id));
+        if self.next_ids.len() > RETRY_TICKS as usize {
+            self.next_ids.pop_front();
+        }
+        self.responses = Some(Vec::new());
+
+        let mut expired: Vec<Expired> = Vec::new();
+        let mut waiting = Vec::new();
+        for entry in std::mem::take(&mut self.waiting) {
+            if let Some(dropped) = expired.iter_mut().find(|e| e.target == entry.target) {
+                dropped.count += 1;
+            } else if self.tick - entry.since >= RETRY_TICKS {
+                expired.push(Expired {
+                    name: entry.name,
+                    target: entry.target,
+                    count: 1,
+                });
+            } else {
+                waiting.push(entry);
+            }
+        }
+        (waiting, expired)
+    }
+
+    /// Whether `target` was handed out within the window, or isn't yet
+    fn is_recent(&self, target: PluginCommandTarget) -> bool {
+        let Some(&(buffers, splits)) = self.next_ids.front() else {
+            return false;
+        };
+        match target {
+            PluginCommandTarget::Buffer(id) => id.0 >= buffers,
+            PluginCommandTarget::Split(id) => id.0 >= splits,
+        }
+    }
+
+    /// Whether commands for `target` are waiting
+    fn is_waiting(&self, target: PluginCommandTarget) -> bool {
+        self.waiting.iter().any(|entry| entry.target == target)
+    }
+
+    /// Hold a command until its target exists
+    fn wait(
+        &mut self,
+        command: PluginCommand,
+        name: &'static str,
+        target: PluginCommandTarget,
+        since: Option<u64>,
+    ) {
+        self.waiting.push(Waiting {
+            command,
+            name,
+            target,
+            since: since.unwrap_or(self.tick),
+        });
+    }
+
+    /// Keep a response for the end of the batch
+    ///
+    /// Gives the response back when no batch is being processed.
+    pub(crate) fn hold_response(&mut self, response: PluginResponse) -> Option<PluginResponse> {
+        match &mut self.responses {
+            Some(responses) => {
+                responses.push(response);
+                None
+            }
+            None => Some(response),
+        }
+    }
+
+    /// End the batch, returning the responses held during it
+    fn finish_tick(&mut self) -> Vec<PluginResponse> {
+        self.responses.take().unwrap_or_default()
+    }
+}
+
+impl Editor {
+    /// Process TypeScript plugin commands
+    ///
+    /// Returns true if any commands were processed
+    pub(super) fn process_plugin_commands(&mut self) -> bool {
+        let commands = self.plugin_manager.process_commands();
+        let (waiting, expired) = self
+            .plugin_command_queue
+            .start_tick(self.next_buffer_id, self.split_manager.next_split_id());
+        for Expired {
+            name,
+            target,
+            count,
+        } in expired
+        {
+            tracing::warn!(
+                "Dropping {} plugin command(s) for {:?} (first: {}): it never appeared",
+                count,
+                target,
+                name
+            );
+        }
+        if commands.is_empty() && waiting.is_empty() {
+            self.plugin_command_queue.finish_tick();
+            self.plugin_manager.commands_applied();
+            return false;
+        }
+
+        tracing::trace!(
+            "process_plugin_commands: processing {} commands, {} waiting",
+            commands.len(),
+            waiting.len()
+        );
+
+        let waiting = waiting
+            .into_iter()
+            .map(|entry| (entry.command, Some(entry.since)));
+        for (command, since) in waiting.chain(commands.into_iter().map(|c| (c, None))) {
+            self.run_or_hold_plugin_command(command, since);
+        }
+        self.plugin_manager.commands_applied();
+        for response in self.plugin_command_queue.finish_tick() {
+            self.plugin_manager.deliver_response(response);
+        }
+        self.validate_if_enabled("plugin commands");
+
+        true
+    }
+
+    /// Run a plugin command, or hold it back while its target is being created
+    ///
+    /// `since` is the tick a held command first arrived in.
+    fn run_or_hold_plugin_command(&mut self, command: PluginCommand, since: Option<u64>) {
+        if let Some((name, target)) = command.target() {
+            let queue = &self.plugin_command_queue;
+            let hold = queue.is_waiting(target)
+                || (!self.plugin_target_exists(target)
+                    && (since.is_some() || queue.is_recent(target)));
+            if hold {
+                self.plugin_command_queue.wait(command, name, target, since);
+                return;
+            }
+        }
+        tracing::trace!(
+            "process_plugin_commands: handling command {:?}",
+            std::mem::discriminant(&command)
+        );
+        if let Err(e) = self.handle_plugin_command(command) {
+            tracing::error!("Error handling TypeScript plugin command: {}", e);
+        }
+    }
+
+    /// Whether a buffer or split is fully set up
+    fn plugin_target_exists(&self, target: PluginCommandTarget) -> bool {
+        match target {
+            PluginCommandTarget::Buffer(id) => self.buffers.contains_key(&id),
+            PluginCommandTarget::Split(id) => {
+                self.split_view_states.contains_key(&id)
+                    && self.split_manager.get_buffer_id(id).is_some()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::event::{BufferId, SplitId};
+
+    fn refresh(buffer: usize) -> PluginCommand {
+        PluginCommand::RefreshLines {
+            buffer_id: BufferId(buffer),
+        }
+    }
+
+    fn hold(queue: &mut PluginCommandQueue, buffer: usize, since: Option<u64>) {
+        let command = refresh(buffer);
+        let (name, target) = command.target().unwrap();
+        queue.wait(command, name, target, since);
+    }
+
+    #[test]
+    fn test_recent_ids_cover_the_window() {
+        let mut queue = PluginCommandQueue::default();
+        queue.start_tick(5, 3);
+        assert!(queue.is_recent(PluginCommandTarget::Buffer(BufferId(5))));
+        assert!(queue.is_recent(PluginCommandTarget::Buffer(BufferId(9))));
+        assert!(!queue.is_recent(PluginCommandTarget::Buffer(BufferId(4))));
+        assert!(queue.is_recent(PluginCommandTarget::Split(SplitId(3))));
+        assert!(!queue.is_recent(PluginCommandTarget::Split(SplitId(2))));
+
+        // Buffer 5 was handed out in the window, so it stays recent until
+        // the window has passed
+        for _ in 1..RETRY_TICKS {
+            queue.start_tick(6, 3);
+        }
+        assert!(queue.is_recent(PluginCommandTarget::Buffer(BufferId(5))));
+        queue.start_tick(6, 3);
+        assert!(!queue.is_recent(PluginCommandTarget::Buffer(BufferId(5))));
+    }
+
+    #[test]
+    fn test_waiting_commands_keep_their_order() {
+        let mut queue = PluginCommandQueue::default();
+        queue.start_tick(1, 1);
+        hold(&mut queue, 7, None);
+        hold(&mut queue, 8, None);
+        hold(&mut queue, 7, None);
+        assert!(queue.is_waiting(PluginCommandTarget::Buffer(BufferId(7))));
+        assert!(!queue.is_waiting(PluginCommandTarget::Buffer(BufferId(9))));
+
+        let (waiting, expired) = queue.start_tick(1, 1);
+        assert!(expired.is_empty());
+        let buffers: Vec<_> = waiting.iter().map(|w| w.target).collect();
+        assert_eq!(
+            buffers,
+            vec![
+                PluginCommandTarget::Buffer(BufferId(7)),
+                PluginCommandTarget::Buffer(BufferId(8)),
+                PluginCommandTarget::Buffer(BufferId(7)),
+            ]
+        );
+        assert!(waiting.iter().all(|w| w.since == 1));
+    }
+
+    #[test]
+    fn test_commands_expire_per_target() {
+        let mut queue = PluginCommandQueue::default();
+        queue.start_tick(1, 1);
+        hold(&mut queue, 7, None);
+        hold(&mut queue, 7, None);
+        for _ in 0..RETRY_TICKS - 1 {
+            let (waiting, expired) = queue.start_tick(1, 1);
+            assert!(expired.is_empty());
+            for entry in waiting {
+                queue.wait(entry.command, entry.name, entry.target, Some(entry.since));
+            }
+        }
+        // Buffer 8 arrived later and has time left
+        hold(&mut queue, 8, None);
+
+        let (waiting, expired) = queue.start_tick(1, 1);
+        assert_eq!(
+            expired,
+            vec![Expired {
+                name: "RefreshLines",
+                target: PluginCommandTarget::Buffer(BufferId(7)),
+                count: 2,
+            }]
+        );
+        assert_eq!(waiting.len(), 1);
+        assert_eq!(waiting[0].target, PluginCommandTarget::Buffer(BufferId(8)));
+    }
+
+    #[test]
+    fn test_responses_are_held_during_a_batch() {
+        let response = || PluginResponse::BufferText {
+            request_id: 1,
+            text: Ok(String::new()),
+        };
+        let mut queue = PluginCommandQueue::default();
+        assert!(queue.hold_response(response()).is_some());
+
+        queue.start_tick(1, 1);
+        assert!(queue.hold_response(response()).is_none());
+        assert!(queue.hold_response(response()).is_none());
+        assert_eq!(queue.finish_tick().len(), 2);
+        assert!(queue.hold_response(response()).is_some());
+    }
+}
@@ -2,12 +2,18 @@
 //!
 //! This module groups plugin commands by domain for better maintainability.
 
-use crate::model::event::{BufferId, CursorId, Event, SplitId};
+use crate::model::event::{BufferId, CursorId, EditSource, Event, SplitId};
+use crate::services::file_provider::is_remote_path;
+use crate::services::lsp::manager::label_server;
+use crate::services::lsp::uri::path_or_uri_to_path;
 use crate::services::plugins::api::{
     LayoutHints, MenuPosition, PluginResponse, ViewTransformPayload,
 };
 use crate::view::overlay::{OverlayHandle, OverlayNamespace};
 use crate::view::split::SplitViewState;
+use crate::view::theme::{DecorationColor, ThemedStyle};
+use crate::view::ui::winbar::{self, WinbarSegment};
+use rust_i18n::t;
 use std::io;
 
 use super::Editor;
@@ -36,8 +42,8 @@ impl Editor {
         buffer_id: BufferId,
         namespace: Option<OverlayNamespace>,
         range: std::ops::Range<usize>,
-        color: (u8, u8, u8),
-        bg_color: Option<(u8, u8, u8)>,
+        color: DecorationColor,
+        bg_color: Option<DecorationColor>,
         underline: bool,
         bold: bool,
         italic: bool,
@@ -123,13 +129,13 @@ impl Editor {
         virtual_text_id: String,
         position: usize,
         text: String,
-        color: (u8, u8, u8),
+        color: DecorationColor,
         use_bg: bool,
         before: bool,
     ) {
         if let Some(state) = self.buffers.get_mut(&buffer_id) {
             use crate::view::virtual_text::VirtualTextPosition;
-            use ratatui::style::{Color, Style};
+            use ratatui::style::Modifier;
 
             let vtext_position = if before {
                 VirtualTextPosition::BeforeChar
@@ -139,11 +145,20 @@ impl Editor {
 
             let style = if use_bg {
                 // For background colors, use the color as background with a space character
-                Style::default().bg(Color::Rgb(color.0, color.1, color.2))
+                ThemedStyle {
+                    fg: None,
+                    bg: Some(color),
+                    add_modifier: Modifier::empty(),
+                }
             } else {
                 // For foreground colors, use the color as foreground
-                Style::default().fg(Color::Rgb(color.0, color.1, color.2))
+                ThemedStyle {
+                    fg: Some(color),
+                    bg: None,
+                    add_modifier: Modifier::empty(),
+                }
             };
+            let fixed_style = style.fixed();
 
             // Remove any existing virtual text with this ID first
             state
@@ -151,15 +166,18 @@ impl Editor {
                 .remove_by_id(&mut state.marker_list, &virtual_text_id);
 
             // Add the new virtual text
-            state.virtual_texts.add_with_id(
+            let id = state.virtual_texts.add_with_id(
                 &mut state.marker_list,
                 position,
                 text,
-                style,
+                fixed_style.unwrap_or_default(),
                 vtext_position,
                 0, // priority
                 virtual_text_id,
             );
+            if fixed_style.is_none() {
+                state.virtual_texts.set_themed_style(id, style);
+            }
         }
     }
 
@@ -202,15 +220,15 @@ impl Editor {
         buffer_id: BufferId,
         position: usize,
         text: String,
-        fg_color: (u8, u8, u8),
-        bg_color: Option<(u8, u8, u8)>,
+        fg_color: DecorationColor,
+        bg_color: Option<DecorationColor>,
         above: bool,
         namespace: String,
         priority: i32,
     ) {
         if let Some(state) = self.buffers.get_mut(&buffer_id) {
             use crate::view::virtual_text::{VirtualTextNamespace, VirtualTextPosition};
-            use ratatui::style::{Color, Style};
+            use ratatui::style::Modifier;
 
             let placement = if above {
                 VirtualTextPosition::LineAbove
@@ -218,21 +236,26 @@ impl Editor {
                 VirtualTextPosition::LineBelow
             };
 
-            let mut style = Style::default().fg(Color::Rgb(fg_color.0, fg_color.1, fg_color.2));
-            if let Some(bg) = bg_color {
-                style = style.bg(Color::Rgb(bg.0, bg.1, bg.2));
-            }
+            let style = ThemedStyle {
+                fg: Some(fg_color),
+                bg: bg_color,
+                add_modifier: Modifier::empty(),
+            };
+            let fixed_style = style.fixed();
             let ns = VirtualTextNamespace::from_string(namespace);
 
-            state.virtual_texts.add_line(
+            let id = state.virtual_texts.add_line(
                 &mut state.marker_list,
                 position,
                 text,
-                style,
+                fixed_style.unwrap_or_default(),
                 placement,
                 ns,
                 priority,
             );
+            if fixed_style.is_none() {
+                state.virtual_texts.set_themed_style(id, style);
+            }
         }
     }
 
@@ -470,6 +493,14 @@ impl Editor {
         }
     }
 
+    /// Handle SetWinbarSegment command
+    pub(super) fn handle_set_winbar_segment(&mut self, split_id: SplitId, segment: WinbarSegment) {
+        match self.split_view_states.get_mut(&split_id) {
+            Some(view_state) => winbar::set_segment(&mut view_state.winbar_segments, segment),
+            None => tracing::warn!("Cannot set winbar segment of unknown split {:?}", split_id),
+        }
+    }
+
     /// Handle DistributeSplitsEvenly command
     pub(super) fn handle_distribute_splits_evenly(&mut self) {
         // The split_ids parameter is currently ignored - we distribute ALL splits evenly
@@ -616,6 +647,77 @@ impl Editor {
         self.send_plugin_response(PluginResponse::HighlightsComputed { request_id, spans });
     }
 
+    /// Handle ByteToLineCol command
+    pub(super) fn handle_byte_to_line_col(
+        &mut self,
+        buffer_id: BufferId,
+        byte: usize,
+        request_id: u64,
+    ) {
+        let position = self.indexed_buffer(buffer_id).and_then(|buffer| {
+            if byte > buffer.len() {
+                return Err(format!(
+                    "Byte {} is past the end of buffer {:?} ({} bytes)",
+                    byte,
+                    buffer_id,
+                    buffer.len()
+                ));
+            }
+            buffer
+                .offset_to_position(byte)
+                .map(|position| (position.line, position.column))
+                .ok_or_else(|| format!("Buffer {:?} has no line index", buffer_id))
+        });
+        self.send_plugin_response(PluginResponse::LineCol {
+            request_id,
+            position,
+        });
+    }
+
+    /// Handle LineColToByte command
+    pub(super) fn handle_line_col_to_byte(
+        &mut self,
+        buffer_id: BufferId,
+        line: usize,
+        column: usize,
+        request_id: u64,
+    ) {
+        let offset = self.indexed_buffer(buffer_id).and_then(|buffer| {
+            let start = buffer.line_start_offset(line).ok_or_else(|| {
+                format!(
+                    "Line {} is past the end of buffer {:?} ({} lines)",
+                    line,
+                    buffer_id,
+                    buffer.line_count().unwrap_or(0)
+                )
+            })?;
+            // The line ends before its line feed
+            let end = buffer
+                .line_start_offset(line + 1)
+                .map_or(buffer.len(), |next| next - 1);
+            Ok(start + column.min(end - start))
+        });
+        self.send_plugin_response(PluginResponse::ByteOffset { request_id, offset });
+    }
+
+    /// A buffer whose line index can answer position conversions
+    fn indexed_buffer(
+        &self,
+        buffer_id: BufferId,
+    ) -> Result<&crate::model::buffer::TextBuffer, String> {
+        let state = self
+            .buffers
+            .get(&buffer_id)
+            .ok_or_else(|| format!("Buffer {:?} not found", buffer_id))?;
+        if state.buffer.line_count().is_none() {
+            return Err(format!(
+                "Buffer {:?} is too large to have a line index",
+                buffer_id
+            ));
+        }
+        Ok(&state.buffer)
+    }
+
     // ==================== Text Editing Commands ====================
 
     /// Handle InsertText command
@@ -625,6 +727,9 @@ impl Editor {
         position: usize,
         text: String,
     ) {
+        if self.reject_protected_plugin_edit(buffer_id, "insert_text") {
+            return;
+        }
         if let Some(state) = self.buffers.get_mut(&buffer_id) {
             let event = Event::Insert {
                 position,
@@ -633,7 +738,7 @@ impl Editor {
             };
             state.apply(&event);
             if let Some(log) = self.event_logs.get_mut(&buffer_id) {
-                log.append(event);
+                log.append_from(event, EditSource::Plugin);
             }
         }
     }
@@ -644,6 +749,9 @@ impl Editor {
         buffer_id: BufferId,
         range: std::ops::Range<usize>,
     ) {
+        if self.reject_protected_plugin_edit(buffer_id, "delete_range") {
+            return;
+        }
         if let Some(state) = self.buffers.get_mut(&buffer_id) {
             let deleted_text = state.get_text_range(range.start, range.end);
             let event = Event::Delete {
@@ -653,13 +761,78 @@ impl Editor {
             };
             state.apply(&event);
             if let Some(log) = self.event_logs.get_mut(&buffer_id) {
-                log.append(event);
+                log.append_from(event, EditSource::Plugin);
+            }
+        }
+    }
+
+    /// Handle ApplyEdits command
+    ///
+    /// The edits apply together as one undo step named `label`, or not at
+    /// all when a range is outside the buffer or overlaps another.
+    pub(super) fn handle_apply_edits(
+        &mut self,
+        buffer_id: BufferId,
+        mut edits: Vec<(std::ops::Range<usize>, String)>,
+        label: Option<String>,
+    ) {
+        if self.reject_protected_plugin_edit(buffer_id, "apply_edits") {
+            return;
+        }
+        let Some(state) = self.buffers.get_mut(&buffer_id) else {
+            return;
+        };
+        edits.sort_by_key(|(range, _)| range.start);
+        let len = state.buffer.len();
+        let fits = edits
+            .iter()
+            .all(|(range, _)| range.start <= range.end && range.end <= len)
+            && edits
+                .windows(2)
+                .all(|pair| pair[0].0.end <= pair[1].0.start);
+        if !fits {
+            tracing::warn!(
+                "Ignoring {} plugin edits to buffer {:?}: ranges overlap or are out of bounds",
+                edits.len(),
+                buffer_id
+            );
+            return;
+        }
+
+        // From the last edit backwards, so earlier offsets stay valid
+        let cursor_id = state.cursors.primary_id();
+        let mut events = Vec::with_capacity(edits.len() * 2);
+        for (range, text) in edits.into_iter().rev() {
+            if !range.is_empty() {
+                events.push(Event::Delete {
+                    deleted_text: state.get_text_range(range.start, range.end),
+                    range: range.clone(),
+                    cursor_id,
+                });
             }
+            if !text.is_empty() {
+                events.push(Event::Insert {
+                    position: range.start,
+                    text,
+                    cursor_id,
+                });
+            }
+        }
+        if let Err(e) = self.apply_events_to_buffer_as_bulk_edit(
+            buffer_id,
+            events,
+            "Plugin edits".to_string(),
+            label,
+        ) {
+            tracing::warn!("Failed to apply plugin edits to {:?}: {}", buffer_id, e);
         }
     }
 
     /// Handle InsertAtCursor command
     pub(super) fn handle_insert_at_cursor(&mut self, text: String) {
+        if self.reject_protected_plugin_edit(self.active_buffer(), "insert_at_cursor") {
+            return;
+        }
         // Insert text at current cursor position in active buffer
         let state = self.active_state_mut();
         let cursor_pos = state.cursors.primary().position;
@@ -669,11 +842,15 @@ impl Editor {
             cursor_id: CursorId(0),
         };
         state.apply(&event);
-        self.active_event_log_mut().append(event);
+        self.active_event_log_mut()
+            .append_from(event, EditSource::Plugin);
     }
 
     /// Handle DeleteSelection command
     pub(super) fn handle_delete_selection(&mut self) {
+        if self.reject_protected_plugin_edit(self.active_buffer(), "delete_selection") {
+            return;
+        }
         // Get deletions from state (same logic as cut_selection but without copy)
         let deletions: Vec<_> = {
             let state = self.active_state();
@@ -703,7 +880,8 @@ impl Editor {
 
             // Apply events
             for event in events {
-                self.active_event_log_mut().append(event.clone());
+                self.active_event_log_mut()
+                    .append_from(event.clone(), EditSource::Plugin);
                 self.apply_event_to_active_buffer(&event);
             }
         }
@@ -754,6 +932,29 @@ impl Editor {
         }
     }
 
+    /// Path a plugin asked to open, decoding `file://` URIs
+    ///
+    /// Other URIs don't name a local file; they get a status message and None.
+    fn plugin_path_to_open(&mut self, path: std::path::PathBuf) -> Option<std::path::PathBuf> {
+        let Some(text) = path.to_str() else {
+            return Some(path);
+        };
+        // URLs such as ssh://host/path are opened through their provider
+        if is_remote_path(&path) {
+            return Some(path);
+        }
+        match path_or_uri_to_path(text) {
+            Ok(path) => Some(path),
+            Err(e) => {
+                tracing::warn!("Plugin asked to open {}: {}", text, e);
+                self.set_status_message(
+                    t!("error.cannot_open_uri", uri = text, error = e.to_string()).to_string(),
+                );
+                None
+            }
+        }
+    }
+
     /// Handle OpenFileAtLocation command
     pub(super) fn handle_open_file_at_location(
         &mut self,
@@ -761,10 +962,21 @@ impl Editor {
         line: Option<usize>,
         column: Option<usize>,
     ) -> io::Result<()> {
-        // Open the file
-        if let Err(e) = self.open_file(&path) {
-            tracing::error!("Failed to open file from plugin: {}", e);
+        let Some(path) = self.plugin_path_to_open(path) else {
             return Ok(());
+        };
+
+        // Open the file, or show the directory in the file explorer
+        match self.open_path(&path) {
+            Ok(Some(_)) => {}
+            Ok(None) => return Ok(()),
+            Err(e) => {
+                tracing::error!("Failed to open file from plugin: {}", e);
+                self.set_status_message(
+                    t!("file.error_opening", error = e.to_string()).to_string(),
+                );
+                return Ok(());
+            }
         }
 
         // If line/column specified, jump to that location
@@ -782,6 +994,10 @@ impl Editor {
         line: Option<usize>,
         column: Option<usize>,
     ) -> io::Result<()> {
+        let Some(path) = self.plugin_path_to_open(path) else {
+            return Ok(());
+        };
+
         // Save current split's view state before switching
         self.save_current_split_view_state();
 
@@ -794,9 +1010,16 @@ impl Editor {
         self.restore_current_split_view_state();
 
         // Open the file in the now-active split
-        if let Err(e) = self.open_file(&path) {
-            tracing::error!("Failed to open file from plugin: {}", e);
-            return Ok(());
+        match self.open_path(&path) {
+            Ok(Some(_)) => {}
+            Ok(None) => return Ok(()),
+            Err(e) => {
+                tracing::error!("Failed to open file from plugin: {}", e);
+                self.set_status_message(
+                    t!("file.error_opening", error = e.to_string()).to_string(),
+                );
+                return Ok(());
+            }
         }
 
         // Jump to the specified location (or default to start)
@@ -806,9 +1029,13 @@ impl Editor {
 
     /// Handle OpenFileInBackground command
     pub(super) fn handle_open_file_in_background(&mut self, path: std::path::PathBuf) {
+        let Some(path) = self.plugin_path_to_open(path) else {
+            return;
+        };
         // Open file in a new tab without switching to it
         if let Err(e) = self.open_file_no_focus(&path) {
             tracing::error!("Failed to open file in background: {}", e);
+            self.set_status_message(t!("file.error_opening", error = e.to_string()).to_string());
         } else {
             tracing::info!("Opened file in background: {:?}", path);
         }
@@ -824,6 +1051,24 @@ impl Editor {
         }
     }
 
+    /// Handle CreateBuffer command
+    pub(super) fn handle_create_buffer(
+        &mut self,
+        content: String,
+        language: Option<String>,
+        name_hint: Option<String>,
+        request_id: u64,
+    ) {
+        let buffer_id = self.create_untitled_buffer(&content, language.as_deref(), name_hint);
+        if let Ok(buffer_id) = buffer_id {
+            tracing::info!("Created untitled buffer {:?}", buffer_id);
+        }
+        self.send_plugin_response(PluginResponse::BufferCreated {
+            request_id,
+            buffer_id,
+        });
+    }
+
     /// Handle CloseBuffer command
     pub(super) fn handle_close_buffer(&mut self, buffer_id: BufferId) {
         match self.close_buffer(buffer_id) {
@@ -903,23 +1148,31 @@ impl Editor {
     }
 
     /// Handle SetLineIndicator command
+    #[allow(clippy::too_many_arguments)]
     pub(super) fn handle_set_line_indicator(
         &mut self,
         buffer_id: BufferId,
         line: usize,
         namespace: String,
         symbol: String,
-        color: (u8, u8, u8),
+        color: DecorationColor,
         priority: i32,
+        column: Option<String>,
     ) {
         if let Some(state) = self.buffers.get_mut(&buffer_id) {
             // Convert line number to byte offset for marker-based tracking
             let byte_offset = state.buffer.line_start_offset(line).unwrap_or(0);
-            let indicator = crate::view::margin::LineIndicator::new(
+            let mut indicator = crate::view::margin::LineIndicator::new(
                 symbol,
-                ratatui::style::Color::Rgb(color.0, color.1, color.2),
+                color.rgb().unwrap_or(ratatui::style::Color::Reset),
                 priority,
             );
+            if color.rgb().is_none() {
+                indicator = indicator.with_theme_color(color);
+            }
+            if let Some(column) = column {
+                indicator = indicator.in_column(column);
+            }
             state
                 .margins
                 .set_line_indicator(byte_offset, namespace, indicator);
@@ -1013,11 +1266,71 @@ impl Editor {
         }
     }
 
+    /// Handle ClosePrompt command
+    ///
+    /// Only plugin prompts are closed, and only when the type matches, so a stale
+    /// request can't cancel a prompt the user opened in the meantime. Cancelling goes
+    /// through `cancel_prompt` so the `prompt_cancelled` hook fires just like Escape.
+    pub(super) fn handle_close_prompt(&mut self, prompt_type: Option<String>) {
+        use crate::view::prompt::PromptType;
+        let matches = match self.prompt.as_ref().map(|p| &p.prompt_type) {
+            Some(PromptType::Plugin { custom_type }) => {
+                prompt_type.as_ref().is_none_or(|t| t == custom_type)
+            }
+            _ => false,
+        };
+        if !matches {
+            tracing::debug!("ClosePrompt({:?}) ignored: no matching prompt", prompt_type);
+            return;
+        }
+
+        // The user didn't cancel anything, so keep the current status message
+        let status_message = self.status_message.take();
+        self.cancel_prompt();
+        self.status_message = status_message;
+    }
+
+    /// Handle AcceptPromptSuggestion command
+    ///
+    /// Selects the suggestion and confirms the prompt, the same as clicking it.
+    pub(super) fn handle_accept_prompt_suggestion(&mut self, index: usize) -> io::Result<()> {
+        use crate::view::prompt::PromptType;
+        let Some(prompt) = self.prompt.as_mut() else {
+            tracing::debug!(
+                "AcceptPromptSuggestion({}) ignored: no active prompt",
+                index
+            );
+            return Ok(());
+        };
+        if !matches!(prompt.prompt_type, PromptType::Plugin { .. })
+            || index >= prompt.suggestions.len()
+        {
+            tracing::debug!(
+                "AcceptPromptSuggestion({}) ignored: not a plugin prompt or out of range",
+                index
+            );
+            return Ok(());
+        }
+
+        prompt.selected_suggestion = Some(index);
+        self.handle_action(crate::input::keybindings::Action::PromptConfirm)
+    }
+
     // ==================== Command/Mode Registration ====================
 
     /// Handle RegisterCommand command
-    pub(super) fn handle_register_command(&self, command: crate::input::commands::Command) {
-        self.command_registry.read().unwrap().register(command);
+    pub(super) fn handle_register_command(
+        &self,
+        command: crate::input::commands::Command,
+        dry_run: bool,
+        args: Vec<crate::input::command_args::CommandArg>,
+    ) {
+        let registry = self.command_registry.read().unwrap();
+        if let crate::input::keybindings::Action::PluginAction(action_name) = &command.action {
+            registry.set_dry_run_support(action_name, dry_run);
+            registry.set_action_args(action_name, args);
+        }
+        registry.register(command);
     }
 
     /// Handle UnregisterCommand command
@@ -1032,11 +1345,14 @@ impl Editor {
         parent: Option<String>,
         bindings: Vec<(String, String)>,
         read_only: bool,
+        plugin: Option<String>,
     ) {
         use super::parse_key_string;
         use crate::input::buffer_mode::BufferMode;
 
-        let mut mode = BufferMode::new(name.clone()).with_read_only(read_only);
+        let mut mode = BufferMode::new(name.clone())
+            .with_read_only(read_only)
+            .with_plugin(plugin);
 
         if let Some(parent_name) = parent {
             mode = mode.with_parent(parent_name);
@@ -1097,7 +1413,13 @@ impl Editor {
             method
         );
         let error = if let Some(lsp) = self.lsp.as_mut() {
-            if let Some(handle) = lsp.get_or_spawn(&language) {
+            // A label names one of a language's several servers
+            let handle = if label_server(&language).is_some() {
+                lsp.get_or_spawn_server(&language)
+            } else {
+                lsp.get_or_spawn(&language)
+            };
+            if let Some(handle) = handle {
                 if let Err(e) = handle.send_plugin_request(request_id, method, params) {
                     Some(e)
                 } else {
@@ -1122,5 +1444,6 @@ impl Editor {
     /// Handle SetClipboard command
     pub(super) fn handle_set_clipboard(&mut self, text: String) {
         self.clipboard.copy(text);
+        self.report_slow_clipboard();
     }
 }
@@ -0,0 +1,63 @@
+//! Plugin hook handlers that set themselves off
+//!
+//! The plugin thread cuts off a handler once the chain of hooks its commands
+//! set off gets deeper than `editor.plugin_hook_chain_depth`, or it runs more
+//! than `editor.plugin_hook_repeat_limit` times within the chain (see
+//! [`crate::services::plugins::hook_guard`]). Each cut is logged as a warning
+//! and shown as a notification naming the plugin and hook, and counted per
+//! plugin.
+
+use rust_i18n::t;
+
+use super::Editor;
+use crate::model::event::BufferId;
+use crate::services::plugins::hook_guard::HookLoopLimits;
+use crate::view::notifications::NotificationSeverity;
+
+impl Editor {
+    /// Limits of hook chains set off by plugin commands, from the config
+    pub(super) fn plugin_hook_loop_limits(&self) -> HookLoopLimits {
+        HookLoopLimits {
+            max_depth: self.config.editor.plugin_hook_chain_depth,
+            max_repeats: self.config.editor.plugin_hook_repeat_limit,
+        }
+    }
+
+    /// Times a handler of `plugin` was cut off for setting itself off
+    pub fn plugin_hook_loop_trips(&self, plugin: &str) -> u64 {
+        self.plugin_hook_loop_trips
+            .get(plugin)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// A handler of `plugin` was cut off for the rest of its chain
+    pub(super) fn handle_hook_loop_cut(
+        &mut self,
+        plugin: String,
+        hook: String,
+        buffer_id: Option<BufferId>,
+        depth: u32,
+        runs: u32,
+    ) {
+        let trips = self
+            .plugin_hook_loop_trips
+            .entry(plugin.clone())
+            .or_default();
+        *trips += 1;
+        tracing::warn!(
+            "Cut off plugin '{}' handler of '{}' for buffer {:?} at hook chain depth {} \
+             ({} runs); cut off {} time(s) so far",
+            plugin,
+            hook,
+            buffer_id,
+            depth,
+            runs,
+            trips
+        );
+        self.notify(
+            NotificationSeverity::Warning,
+            t!("plugins.hook_loop_cut", plugin = plugin, hook = hook),
+        );
+    }
+}
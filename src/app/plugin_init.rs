@@ -0,0 +1,138 @@
+//! Plugin runtime startup
+//!
+//! Loads TypeScript plugins when the plugin runtime starts, and keeps the
+//! editor usable when it doesn't: a runtime that fails to start (V8 refusing to
+//! initialize on a locked-down system, a full temp dir, ...) leaves a no-op
+//! [`PluginManager`], a warning explaining what happened, and a command to try
+//! again. A plugin that fails to load is reported by name and the others load
+//! as usual.
+
+use crate::services::plugins::manager::{PluginLoadError, PLUGIN_RUNTIME_FAILED_CONTEXT};
+use crate::services::plugins::PluginManager;
+use crate::view::notifications::NotificationSeverity;
+use rust_i18n::t;
+use std::path::Path;
+use std::sync::Arc;
+
+use super::Editor;
+
+/// Load TypeScript plugins from the directories that have them, returning
+/// the plugins that failed to load
+pub(super) fn load_plugins(
+    plugin_manager: &PluginManager,
+    working_dir: &Path,
+) -> Vec<PluginLoadError> {
+    // Load TypeScript plugins from multiple directories:
+    // 1. Next to the executable (for cargo-dist installations)
+    // 2. In the working directory (for development/local usage)
+    // 3. From embedded plugins (for cargo-binstall, when embed-plugins feature is enabled)
+    let mut plugin_dirs: Vec<std::path::PathBuf> = vec![];
+
+    // Check next to executable first (for cargo-dist installations)
+    if let Ok(exe_path) = std::env::current_exe() {
+        if let Some(exe_dir) = exe_path.parent() {
+            let exe_plugin_dir = exe_dir.join("plugins");
+            if exe_plugin_dir.exists() {
+                plugin_dirs.push(exe_plugin_dir);
+            }
+        }
+    }
+
+    // Then check working directory (for development)
+    let working_plugin_dir = working_dir.join("plugins");
+    if working_plugin_dir.exists() && !plugin_dirs.contains(&working_plugin_dir) {
+        plugin_dirs.push(working_plugin_dir);
+    }
+
+    // If no disk plugins found, try embedded plugins (cargo-binstall builds)
+    #[cfg(feature = "embed-plugins")]
+    if plugin_dirs.is_empty() {
+        if let Some(embedded_dir) = crate::services::plugins::embedded::get_embedded_plugins_dir() {
+            tracing::info!("Using embedded plugins from: {:?}", embedded_dir);
+            plugin_dirs.push(embedded_dir.clone());
+        }
+    }
+
+    if plugin_dirs.is_empty() {
+        tracing::debug!(
+            "No plugins directory found next to executable or in working dir: {:?}",
+            working_dir
+        );
+    }
+
+    // Load from all found plugin directories
+    let mut errors = Vec::new();
+    for plugin_dir in plugin_dirs {
+        tracing::info!("Loading TypeScript plugins from: {:?}", plugin_dir);
+        errors.extend(plugin_manager.load_plugins_from_dir(&plugin_dir));
+    }
+    errors
+}
+
+impl Editor {
+    /// Whether the plugin runtime was requested but failed to start
+    pub fn plugin_runtime_failed(&self) -> bool {
+        self.plugin_manager.init_error().is_some()
+    }
+
+    /// Explain a failed plugin runtime start in the status bar and warning log
+    pub(super) fn report_plugin_init_failure(&mut self) {
+        let Some(error) = self.plugin_manager.init_error().map(str::to_string) else {
+            self.active_custom_contexts
+                .remove(PLUGIN_RUNTIME_FAILED_CONTEXT);
+            return;
+        };
+        tracing::warn!(
+            "Plugin runtime failed to start, continuing without plugins: {}. \
+             Run with --no-plugins to skip plugin initialization.",
+            error
+        );
+        self.active_custom_contexts
+            .insert(PLUGIN_RUNTIME_FAILED_CONTEXT.to_string());
+        self.notify(
+            NotificationSeverity::Error,
+            t!("plugins.init_failed", error = error),
+        );
+    }
+
+    /// Report plugins that failed to load in the warning log and as
+    /// notifications naming the plugin
+    pub(super) fn report_plugin_load_errors(&mut self, errors: Vec<PluginLoadError>) {
+        for error in errors {
+            tracing::warn!("{}", error);
+            // The first line has the location; the rest is the source excerpt
+            let summary = error.message.lines().next().unwrap_or_default();
+            let message = match &error.plugin {
+                Some(plugin) => t!("plugins.load_failed", plugin = plugin, error = summary),
+                None => summary.to_string().into(),
+            };
+            self.notify(NotificationSeverity::Error, message);
+        }
+    }
+
+    /// Try to start the plugin runtime again after it failed to start
+    pub fn retry_plugin_init(&mut self) {
+        if !self.plugin_runtime_failed() {
+            self.set_status_message(t!("plugins.nothing_to_retry").to_string());
+            return;
+        }
+
+        self.plugin_manager = PluginManager::new(
+            true,
+            Arc::clone(&self.command_registry),
+            Some(self.dir_context.transpile_cache_dir()),
+        );
+        if self.plugin_runtime_failed() {
+            self.report_plugin_init_failure();
+            return;
+        }
+
+        self.active_custom_contexts
+            .remove(PLUGIN_RUNTIME_FAILED_CONTEXT);
+        let errors = load_plugins(&self.plugin_manager, &self.working_dir);
+        self.report_plugin_load_errors(errors);
+        // Let plugins see the lines already on screen
+        self.seen_byte_ranges.clear();
+        self.set_status_message(t!("plugins.init_retried").to_string());
+    }
+}
@@ -0,0 +1,309 @@
+//! Buffer text read by plugins
+//!
+//! `getBufferText` refuses ranges longer than `editor.plugin_text_limit_bytes`
+//! so a plugin cannot copy a huge file into the plugin runtime with one call.
+//! Plugins that need a whole buffer use `readBufferChunks` instead: the editor
+//! sends the buffer a piece at a time from its tick, at most
+//! [`CHUNK_BYTES_PER_TICK`] per tick, through the read's own hook (see
+//! [`buffer_chunks_hook_name`]).
+//!
+//! Every byte sent is charged to the plugin that read it. A plugin that reads
+//! more than [`PLUGIN_TEXT_BUDGET_BYTES`] within [`PLUGIN_TEXT_BUDGET_WINDOW`]
+//! is logged as a warning, once each time it goes over.
+
+use super::Editor;
+use crate::model::event::BufferId;
+use crate::services::plugins::api::PluginResponse;
+use crate::services::plugins::hooks::{buffer_chunks_hook_name, HookArgs};
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// Buffer text a plugin can read within [`PLUGIN_TEXT_BUDGET_WINDOW`] before
+/// a warning is logged
+pub const PLUGIN_TEXT_BUDGET_BYTES: u64 = 256 * 1024 * 1024;
+
+/// Window over which a plugin's reads are added up against its budget
+pub const PLUGIN_TEXT_BUDGET_WINDOW: Duration = Duration::from_secs(60);
+
+/// Most bytes sent to chunked reads in one editor tick
+pub(crate) const CHUNK_BYTES_PER_TICK: usize = 4 * 1024 * 1024;
+
+/// A readBufferChunks read in progress
+pub(crate) struct BufferChunkRead {
+    /// Plugin that started the read
+    pub plugin: String,
+    pub buffer_id: BufferId,
+    /// Most bytes in one piece
+    pub chunk_size: usize,
+    /// Byte offset of the next piece
+    pub offset: usize,
+}
+
+/// Buffer text sent to one plugin
+#[derive(Debug, Default)]
+pub struct PluginTextTransfer {
+    /// Bytes sent since the plugin's first read
+    pub total_bytes: u64,
+    /// Reads served, counting each piece of a chunked read
+    pub reads: u64,
+    /// Reads refused for being longer than the limit
+    pub refused: u64,
+    /// Size and time of the reads within the budget window, oldest first
+    recent: VecDeque<(Instant, u64)>,
+    /// The reads in the window are over budget
+    over_budget: bool,
+}
+
+impl PluginTextTransfer {
+    /// Bytes sent within the budget window of the latest read
+    pub fn recent_bytes(&self) -> u64 {
+        self.recent.iter().map(|(_, bytes)| bytes).sum()
+    }
+
+    /// Count a read of `bytes` at `now`
+    ///
+    /// Returns true if it took the plugin over budget.
+    fn record(&mut self, now: Instant, bytes: u64) -> bool {
+        self.total_bytes += bytes;
+        self.reads += 1;
+        while let Some(&(at, _)) = self.recent.front() {
+            if now.saturating_duration_since(at) <= PLUGIN_TEXT_BUDGET_WINDOW {
+                break;
+            }
+            self.recent.pop_front();
+        }
+        self.recent.push_back((now, bytes));
+
+        let was_over_budget = self.over_budget;
+        self.over_budget = self.recent_bytes() > PLUGIN_TEXT_BUDGET_BYTES;
+        self.over_budget && !was_over_budget
+    }
+}
+
+impl Editor {
+    /// Buffer text sent to a plugin so far, None if it never read any
+    pub fn plugin_text_transfer(&self, plugin: &str) -> Option<&PluginTextTransfer> {
+        self.plugin_text_transfers.get(plugin)
+    }
+
+    /// Charge `bytes` of buffer text to `plugin`
+    fn record_plugin_text(&mut self, plugin: &str, bytes: usize) {
+        let now = self.time_source.now();
+        let transfer = self
+            .plugin_text_transfers
+            .entry(plugin.to_string())
+            .or_default();
+        if transfer.record(now, bytes as u64) {
+            tracing::warn!(
+                "Plugin '{}' read {} bytes of buffer text in the last {}s, over the budget of {}; \
+                 it may be reading whole buffers on every event",
+                plugin,
+                transfer.recent_bytes(),
+                PLUGIN_TEXT_BUDGET_WINDOW.as_secs(),
+                PLUGIN_TEXT_BUDGET_BYTES
+            );
+        }
+    }
+
+    /// Get text from a buffer range (for vi mode yank operations)
+    pub(super) fn handle_get_buffer_text(
+        &mut self,
+        buffer_id: BufferId,
+        start: usize,
+        end: usize,
+        plugin: String,
+        request_id: u64,
+    ) {
+        let limit = self.config.editor.plugin_text_limit_bytes;
+        let result = if let Some(state) = self.buffers.get_mut(&buffer_id) {
+            let len = state.buffer.len();
+            if start > end || end > len {
+                Err(format!(
+                    "Invalid range {}..{} for buffer of length {}",
+                    start, end, len
+                ))
+            } else if (end - start) as u64 > limit {
+                self.plugin_text_transfers
+                    .entry(plugin.clone())
+                    .or_default()
+                    .refused += 1;
+                Err(format!(
+                    "Range {}..{} is {} bytes, more than the {} bytes a plugin can read at once \
+                     (editor.plugin_text_limit_bytes); read smaller ranges or use readBufferChunks",
+                    start,
+                    end,
+                    end - start,
+                    limit
+                ))
+            } else {
+                Ok(state.get_text_range(start, end))
+            }
+        } else {
+            Err(format!("Buffer {:?} not found", buffer_id))
+        };
+
+        if let Ok(text) = &result {
+            self.record_plugin_text(&plugin, text.len());
+        }
+        self.send_plugin_response(PluginResponse::BufferText {
+            request_id,
+            text: result,
+        });
+    }
+
+    /// Start sending a buffer to a plugin in pieces
+    pub(super) fn start_buffer_chunk_read(
+        &mut self,
+        read_id: u32,
+        plugin: String,
+        buffer_id: BufferId,
+        chunk_size: usize,
+    ) {
+        let limit =
+            usize::try_from(self.config.editor.plugin_text_limit_bytes).unwrap_or(usize::MAX);
+        tracing::debug!(
+            "Plugin '{}' reads buffer {:?} in pieces of {} bytes (read {})",
+            plugin,
+            buffer_id,
+            chunk_size,
+            read_id
+        );
+        self.buffer_chunk_reads.insert(
+            read_id,
+            BufferChunkRead {
+                plugin,
+                buffer_id,
+                chunk_size: chunk_size.clamp(1, limit.max(1)),
+                offset: 0,
+            },
+        );
+    }
+
+    /// Send the next pieces of the chunked reads in progress
+    ///
+    /// Returns true if any piece was sent.
+    pub(super) fn poll_buffer_chunk_reads(&mut self) -> bool {
+        if self.buffer_chunk_reads.is_empty() {
+            return false;
+        }
+
+        let mut read_ids: Vec<u32> = self.buffer_chunk_reads.keys().copied().collect();
+        read_ids.sort_unstable();
+
+        let mut sent = 0;
+        for read_id in read_ids {
+            while sent < CHUNK_BYTES_PER_TICK {
+                let Some(read) = self.buffer_chunk_reads.get_mut(&read_id) else {
+                    break;
+                };
+                let offset = read.offset;
+                let (text, done) = match self.buffers.get_mut(&read.buffer_id) {
+                    Some(state) => {
+                        let len = state.buffer.len();
+                        // A few bytes more, to finish a character cut by the chunk size
+                        let wanted = (read.chunk_size + 3).min(len.saturating_sub(offset));
+                        match state.buffer.get_text_range_mut(offset, wanted) {
+                            Ok(mut bytes) => {
+                                bytes.truncate(piece_len(&bytes, read.chunk_size));
+                                let done = offset + bytes.len() >= len;
+                                (bytes, done)
+                            }
+                            Err(e) => {
+                                tracing::warn!(
+                                    "Chunked read {} failed at {}: {}",
+                                    read_id,
+                                    offset,
+                                    e
+                                );
+                                (Vec::new(), true)
+                            }
+                        }
+                    }
+                    // The buffer was closed during the read
+                    None => (Vec::new(), true),
+                };
+                read.offset += text.len();
+                let buffer_id = read.buffer_id;
+                let plugin = if done {
+                    self.buffer_chunk_reads
+                        .remove(&read_id)
+                        .map(|read| read.plugin)
+                        .unwrap_or_default()
+                } else {
+                    read.plugin.clone()
+                };
+
+                sent += text.len().max(1);
+                self.record_plugin_text(&plugin, text.len());
+                let text = String::from_utf8(text)
+                    .unwrap_or_else(|e| String::from_utf8_lossy(e.as_bytes()).into_owned());
+                self.plugin_manager.run_hook(
+                    &buffer_chunks_hook_name(read_id),
+                    HookArgs::BufferChunk {
+                        read_id,
+                        buffer_id,
+                        offset,
+                        text,
+                        done,
+                    },
+                );
+                if done {
+                    break;
+                }
+            }
+        }
+        sent > 0
+    }
+}
+
+/// Length of the piece at the start of `bytes`: up to the last character
+/// boundary within `chunk_size`, or past the first character if that alone is
+/// longer
+fn piece_len(bytes: &[u8], chunk_size: usize) -> usize {
+    if bytes.len() <= chunk_size {
+        return bytes.len();
+    }
+    let is_boundary = |i: usize| i >= bytes.len() || bytes[i] & 0xC0 != 0x80;
+    (1..=chunk_size)
+        .rev()
+        .find(|&i| is_boundary(i))
+        .or_else(|| (chunk_size + 1..bytes.len()).find(|&i| is_boundary(i)))
+        .unwrap_or(bytes.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_piece_len_ends_on_character_boundaries() {
+        let text = "aé✓b".as_bytes(); // 1 + 2 + 3 + 1 bytes
+        assert_eq!(piece_len(text, 10), 7);
+        assert_eq!(piece_len(text, 3), 3);
+        assert_eq!(piece_len(text, 5), 3);
+        assert_eq!(piece_len(text, 6), 6);
+        // A character longer than the chunk size is sent whole
+        assert_eq!(piece_len(&text[3..], 1), 3);
+        assert_eq!(piece_len(b"", 4), 0);
+    }
+
+    #[test]
+    fn test_transfer_warns_once_per_budget_overrun() {
+        let start = Instant::now();
+        let mut transfer = PluginTextTransfer::default();
+        let half = PLUGIN_TEXT_BUDGET_BYTES / 2;
+
+        assert!(!transfer.record(start, half));
+        assert!(!transfer.record(start, half));
+        assert!(transfer.record(start, 1));
+        assert!(!transfer.record(start, 1));
+        assert_eq!(transfer.reads, 4);
+        assert_eq!(transfer.total_bytes, 2 * half + 2);
+
+        // Reads older than the window no longer count
+        let later = start + PLUGIN_TEXT_BUDGET_WINDOW + Duration::from_secs(1);
+        assert!(!transfer.record(later, half));
+        assert_eq!(transfer.recent_bytes(), half);
+        assert!(transfer.record(later, half + 1));
+    }
+}
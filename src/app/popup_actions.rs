@@ -47,6 +47,104 @@ impl Editor {
             return PopupConfirmResult::EarlyReturn;
         }
 
+        // Check if this is the config write conflict popup
+        if self.is_config_conflict_popup() {
+            let action = self
+                .active_state()
+                .popups
+                .top()
+                .and_then(|p| p.selected_item())
+                .and_then(|item| item.data.clone());
+            self.hide_popup();
+            self.handle_config_conflict_response(action.as_deref());
+            return PopupConfirmResult::EarlyReturn;
+        }
+
+        // Check if this is the settings export checklist or import preview
+        if self.is_settings_archive_popup() {
+            let action = self
+                .active_state()
+                .popups
+                .top()
+                .and_then(|p| p.selected_item())
+                .and_then(|item| item.data.clone());
+            self.hide_popup();
+            self.handle_settings_archive_response(action.as_deref());
+            return PopupConfirmResult::EarlyReturn;
+        }
+
+        // Check if this is the legacy state migration popup
+        if self.is_state_migration_popup() {
+            let action = self
+                .active_state()
+                .popups
+                .top()
+                .and_then(|p| p.selected_item())
+                .and_then(|item| item.data.clone());
+            self.hide_popup();
+            self.handle_state_migration_response(action.as_deref());
+            return PopupConfirmResult::EarlyReturn;
+        }
+
+        // Check if this is the session ownership popup
+        if self.is_session_ownership_popup() {
+            let action = self
+                .active_state()
+                .popups
+                .top()
+                .and_then(|p| p.selected_item())
+                .and_then(|item| item.data.clone());
+            self.hide_popup();
+            self.handle_session_ownership_response(action.as_deref());
+            return PopupConfirmResult::EarlyReturn;
+        }
+
+        // Check if this is the "Show Full Message" popup
+        if self.is_full_message_popup() {
+            self.hide_popup();
+            self.copy_full_message();
+            return PopupConfirmResult::EarlyReturn;
+        }
+
+        // Check if this is the project command trust popup
+        if self.is_command_trust_popup() {
+            let action = self
+                .active_state()
+                .popups
+                .top()
+                .and_then(|p| p.selected_item())
+                .and_then(|item| item.data.clone());
+            self.hide_popup();
+            self.handle_command_trust_response(action.as_deref());
+            return PopupConfirmResult::EarlyReturn;
+        }
+
+        // Check if this is the file explorer's context menu
+        if self.is_explorer_context_menu_popup() {
+            let action = self
+                .active_state()
+                .popups
+                .top()
+                .and_then(|p| p.selected_item())
+                .and_then(|item| item.data.clone());
+            self.hide_popup();
+            self.handle_explorer_context_menu_response(action.as_deref());
+            return PopupConfirmResult::EarlyReturn;
+        }
+
+        // Check if this is the blocked commands review
+        if self.is_blocked_commands_popup() {
+            let command_line = self
+                .active_state()
+                .popups
+                .top()
+                .and_then(|p| p.selected_item())
+                .and_then(|item| item.data.clone());
+            self.hide_popup();
+            self.handle_blocked_commands_response(command_line.as_deref());
+            return PopupConfirmResult::EarlyReturn;
+        }
+
         // Check if this is an LSP confirmation popup
         let lsp_confirmation_action = if let Some(popup) = self.active_state().popups.top() {
             if let Some(title) = &popup.title {
@@ -191,6 +289,48 @@ impl Editor {
             return;
         }
 
+        if self.is_config_conflict_popup() {
+            self.hide_popup();
+            self.handle_config_conflict_response(None);
+            return;
+        }
+
+        if self.is_settings_archive_popup() {
+            self.hide_popup();
+            self.handle_settings_archive_response(None);
+            return;
+        }
+
+        if self.is_state_migration_popup() {
+            self.hide_popup();
+            self.handle_state_migration_response(None);
+            return;
+        }
+
+        if self.is_session_ownership_popup() {
+            self.hide_popup();
+            self.handle_session_ownership_response(None);
+            return;
+        }
+
+        if self.is_command_trust_popup() {
+            self.hide_popup();
+            self.handle_command_trust_response(None);
+            return;
+        }
+
+        if self.is_blocked_commands_popup() {
+            self.hide_popup();
+            self.handle_blocked_commands_response(None);
+            return;
+        }
+
+        if self.is_explorer_context_menu_popup() {
+            self.hide_popup();
+            self.handle_explorer_context_menu_response(None);
+            return;
+        }
+
         if self.pending_lsp_confirmation.is_some() {
             self.pending_lsp_confirmation = None;
             self.set_status_message(t!("lsp.startup_cancelled_msg").to_string());
@@ -268,6 +408,10 @@ impl Editor {
     /// Re-filter the completion popup based on current prefix.
     /// If no items match, dismiss the popup.
     fn refilter_completion_popup(&mut self) {
+        if self.dismiss_auto_completion_before_start() {
+            return;
+        }
+
         // Get stored completion items
         let items = match &self.completion_items {
             Some(items) if !items.is_empty() => items.clone(),
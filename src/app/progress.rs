@@ -0,0 +1,238 @@
+//! Progress of long operations, for plugins and the editor itself
+//!
+//! Entries live in [`ProgressList`]. A single entry shows as a segment of
+//! the status bar (clicking it cancels it); with more than one, each shows
+//! as a notification with a Cancel button when it can be cancelled.
+//! Cancelling marks the entry as cancelling and runs its owner's hook (see
+//! [`progress_cancel_hook_name`]); the owner finishes it when it stopped.
+//!
+//! A plugin's entries are finished for it, with a warning, when the plugin
+//! is unloaded or stops updating them for [`STALE_TIMEOUT`].
+
+use rust_i18n::t;
+
+use super::Editor;
+use crate::services::plugins::hooks::{progress_cancel_hook_name, HookArgs};
+use crate::view::notifications::{Notification, NotificationAction, NotificationSeverity};
+use crate::view::progress::{next_progress_id, ProgressList, ProgressOwner, STALE_TIMEOUT};
+
+/// Action id of the Cancel button on a progress notification
+const CANCEL_ACTION: &str = "cancel";
+
+/// Id of the notification showing a progress entry
+fn progress_notification_id(progress_id: u32) -> String {
+    format!("progress-{}", progress_id)
+}
+
+impl Editor {
+    /// Show the progress of one of the editor's own operations
+    ///
+    /// Returns the id for [`Self::update_progress`] and
+    /// [`Self::finish_progress`].
+    pub(crate) fn start_progress(&mut self, title: String) -> u32 {
+        let progress_id = next_progress_id();
+        self.begin_progress(progress_id, ProgressOwner::Editor, title, false);
+        progress_id
+    }
+
+    pub(super) fn begin_progress(
+        &mut self,
+        progress_id: u32,
+        owner: ProgressOwner,
+        title: String,
+        cancellable: bool,
+    ) {
+        let now = self.time_source.now();
+        self.progress
+            .start(progress_id, owner, title, cancellable, now);
+        self.sync_progress_notifications();
+    }
+
+    pub(crate) fn update_progress(
+        &mut self,
+        progress_id: u32,
+        percentage: Option<u8>,
+        message: Option<String>,
+    ) {
+        let now = self.time_source.now();
+        if self.progress.update(progress_id, percentage, message, now) {
+            self.sync_progress_notifications();
+        }
+    }
+
+    pub(crate) fn finish_progress(&mut self, progress_id: u32) {
+        if self.progress.finish(progress_id).is_some() {
+            self.sync_progress_notifications();
+        }
+    }
+
+    /// Finish an entry its plugin left behind, telling the user
+    pub(super) fn abandon_progress(&mut self, progress_id: u32) {
+        let Some(entry) = self.progress.finish(progress_id) else {
+            return;
+        };
+        self.sync_progress_notifications();
+        let plugin = match entry.owner {
+            ProgressOwner::Plugin(plugin) => plugin,
+            ProgressOwner::Editor => return,
+        };
+        tracing::warn!(
+            "Plugin '{}' left progress {} unfinished: {}",
+            plugin,
+            progress_id,
+            entry.title
+        );
+        self.notify(
+            NotificationSeverity::Warning,
+            t!("progress.abandoned", title = entry.title, plugin = plugin).to_string(),
+        );
+    }
+
+    /// Cancel a progress entry, if it can be cancelled
+    ///
+    /// Returns false if there is no such entry or it can't be cancelled.
+    pub fn cancel_progress(&mut self, progress_id: u32) -> bool {
+        if self.progress.cancel(progress_id).is_none() {
+            return false;
+        }
+        self.plugin_manager.run_hook(
+            &progress_cancel_hook_name(progress_id),
+            HookArgs::ProgressCancelled { progress_id },
+        );
+        self.sync_progress_notifications();
+        true
+    }
+
+    /// Cancel the newest entry that can be cancelled
+    pub(super) fn cancel_newest_progress(&mut self) {
+        let newest = self
+            .progress
+            .entries()
+            .iter()
+            .rev()
+            .find(|entry| entry.can_cancel())
+            .map(|entry| entry.id);
+        match newest {
+            Some(progress_id) => {
+                self.cancel_progress(progress_id);
+            }
+            None => self.set_status_message(t!("progress.nothing_to_cancel").to_string()),
+        }
+    }
+
+    /// Long operations in progress, oldest first
+    pub fn progress(&self) -> &ProgressList {
+        &self.progress
+    }
+
+    /// Label for the status bar, when exactly one entry is in progress
+    pub(super) fn progress_status(&self) -> Option<String> {
+        match self.progress.entries() {
+            [entry] => Some(entry.label(self.progress_frame)),
+            _ => None,
+        }
+    }
+
+    /// Finish stale plugin entries and turn the spinner
+    ///
+    /// Returns true if anything visible changed.
+    pub(super) fn check_progress_timers(&mut self) -> bool {
+        if self.progress.is_empty() {
+            return false;
+        }
+        let now = self.time_source.now();
+        let stale = self.progress.stale(now, STALE_TIMEOUT);
+        for progress_id in &stale {
+            self.abandon_progress(*progress_id);
+        }
+        let frame = self.progress.spinner_frame(now);
+        let turned = self.progress.spinning() && frame != self.progress_frame;
+        if turned {
+            self.progress_frame = frame;
+            self.sync_progress_notifications();
+        }
+        turned || !stale.is_empty()
+    }
+
+    /// Handle the answer to a progress notification
+    ///
+    /// Returns false if the notification isn't one of the progress entries.
+    pub(super) fn handle_progress_answer(
+        &mut self,
+        notification_id: &str,
+        action: Option<&str>,
+    ) -> bool {
+        let Some(progress_id) = self
+            .progress_notifications
+            .keys()
+            .copied()
+            .find(|id| progress_notification_id(*id) == notification_id)
+        else {
+            return false;
+        };
+        // Dismissed or not, the notification is gone; it comes back with
+        // the next change while the entry is in progress
+        self.progress_notifications.remove(&progress_id);
+        if action == Some(CANCEL_ACTION) {
+            self.cancel_progress(progress_id);
+        }
+        true
+    }
+
+    /// Show each entry as a notification while there are several, and none
+    /// otherwise
+    fn sync_progress_notifications(&mut self) {
+        let now = self.time_source.now();
+        let stacked = self.progress.entries().len() > 1;
+        let gone: Vec<u32> = self
+            .progress_notifications
+            .keys()
+            .copied()
+            .filter(|id| !stacked || self.progress.get(*id).is_none())
+            .collect();
+        for progress_id in gone {
+            self.progress_notifications.remove(&progress_id);
+            self.notifications
+                .dismiss(&progress_notification_id(progress_id), now);
+        }
+        if !stacked {
+            return;
+        }
+
+        let entries: Vec<(u32, String, bool)> = self
+            .progress
+            .entries()
+            .iter()
+            .map(|entry| {
+                (
+                    entry.id,
+                    entry.label(self.progress_frame),
+                    entry.can_cancel(),
+                )
+            })
+            .collect();
+        for (progress_id, label, can_cancel) in entries {
+            let id = progress_notification_id(progress_id);
+            // Update in place unless the Cancel button comes or goes
+            if self.progress_notifications.get(&progress_id) == Some(&can_cancel)
+                && self.notifications.update_message(&id, label.clone())
+            {
+                continue;
+            }
+            let actions = if can_cancel {
+                vec![NotificationAction {
+                    id: CANCEL_ACTION.to_string(),
+                    label: t!("progress.cancel").to_string(),
+                }]
+            } else {
+                Vec::new()
+            };
+            self.post_notification(
+                Notification::new(id, NotificationSeverity::Info, label)
+                    .with_actions(actions)
+                    .with_timeout(None),
+            );
+            self.progress_notifications.insert(progress_id, can_cancel);
+        }
+    }
+}
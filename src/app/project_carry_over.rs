@@ -0,0 +1,217 @@
+//! State carried across a project switch
+//!
+//! Switching projects ([`Editor::change_working_dir`]) drops the editor and
+//! builds a new one for the new working directory. Path-bound state (open
+//! files, splits, layout) goes through the per-project session as before.
+//! State that belongs to no project is taken from the old editor into a
+//! [`ProjectCarryOver`], kept by the main loop and given to the new editor
+//! once its session is restored:
+//!
+//! - Untitled buffers, with their text, cursor, name and pin, still modified
+//!   if they were
+//! - The internal clipboard and the clipboard ring
+//! - Macro registers
+//! - Search and replace histories, added to those of the new project's
+//!   session
+//!
+//! Recent projects are written to disk whenever they change, so the new
+//! editor reads them back. Overlays aren't carried: they belong to the
+//! plugins and language servers, which restart with the editor and add them
+//! again. On quit nothing is taken, and the carry-over goes away with the
+//! main loop.
+
+use std::collections::HashMap;
+
+use super::types::BufferMetadata;
+use super::Editor;
+use crate::input::keybindings::Action;
+use crate::model::event::BufferId;
+
+/// Version of the carry-over; one of another version is dropped, not
+/// half-applied
+const CARRY_OVER_VERSION: u32 = 1;
+
+/// State of an editor for the one replacing it on a project switch, see the
+/// module docs
+#[derive(Debug)]
+pub struct ProjectCarryOver {
+    version: u32,
+    /// Untitled buffers, oldest first
+    buffers: Vec<UntitledBuffer>,
+    clipboard: String,
+    /// The clipboard ignores the system one, as in tests
+    clipboard_internal_only: bool,
+    /// Texts of the clipboard ring, oldest first
+    clipboard_ring: Vec<String>,
+    macros: HashMap<char, Vec<Action>>,
+    last_macro_register: Option<char>,
+    search_history: Vec<String>,
+    replace_history: Vec<String>,
+}
+
+/// An untitled buffer to create again in the new editor
+#[derive(Debug)]
+struct UntitledBuffer {
+    content: String,
+    modified: bool,
+    /// Name given to the buffer, such as a plugin's name hint
+    display_name: Option<String>,
+    pinned: bool,
+    cursor: usize,
+    anchor: Option<usize>,
+    /// The buffer was the active one
+    active: bool,
+}
+
+impl Editor {
+    /// Take the state carried across a project switch, see
+    /// [`ProjectCarryOver`]
+    pub fn take_carry_over(&mut self) -> ProjectCarryOver {
+        let mut ids: Vec<BufferId> = self.buffers.keys().copied().collect();
+        ids.sort_by_key(|id| id.0);
+        let buffers = ids
+            .into_iter()
+            .filter_map(|id| self.untitled_buffer_carry_over(id))
+            .collect();
+
+        let mut clipboard_ring: Vec<String> = self
+            .clipboard
+            .ring()
+            .iter()
+            .map(|entry| entry.text().to_string())
+            .collect();
+        clipboard_ring.reverse();
+
+        ProjectCarryOver {
+            version: CARRY_OVER_VERSION,
+            buffers,
+            clipboard: self.clipboard.get_internal().to_string(),
+            clipboard_internal_only: self.clipboard.is_internal_only(),
+            clipboard_ring,
+            macros: std::mem::take(&mut self.macros),
+            last_macro_register: self.last_macro_register.take(),
+            search_history: self.search_history.items().to_vec(),
+            replace_history: self.replace_history.items().to_vec(),
+        }
+    }
+
+    /// Bring in the state a previous editor carried across a project switch,
+    /// after this one restored its session
+    pub fn restore_carry_over(&mut self, carry_over: ProjectCarryOver) {
+        if carry_over.version != CARRY_OVER_VERSION {
+            tracing::warn!(
+                "Dropping project switch carry-over of version {} (expected {})",
+                carry_over.version,
+                CARRY_OVER_VERSION
+            );
+            return;
+        }
+
+        // The session's active file stays in front, if it has one
+        let mut active = self.active_buffer();
+        let session_blank = self.is_blank_untitled_buffer(active);
+        for buffer in carry_over.buffers {
+            let was_active = buffer.active;
+            let buffer_id = self.restore_untitled_buffer(buffer);
+            if was_active && session_blank {
+                active = buffer_id;
+            }
+        }
+        if self.buffers.contains_key(&active) {
+            self.set_active_buffer(active);
+        }
+
+        self.clipboard.set_internal(carry_over.clipboard);
+        if carry_over.clipboard_internal_only {
+            self.clipboard.set_internal_only(true);
+        }
+        for text in &carry_over.clipboard_ring {
+            self.clipboard.ring_mut().push(text);
+        }
+        self.macros.extend(carry_over.macros);
+        if carry_over.last_macro_register.is_some() {
+            self.last_macro_register = carry_over.last_macro_register;
+        }
+        // Merged into the project's own, the carried ones newest
+        for item in carry_over.search_history {
+            self.search_history.push(item);
+        }
+        for item in carry_over.replace_history {
+            self.replace_history.push(item);
+        }
+    }
+
+    /// What carries `buffer_id` over, if it's an untitled buffer with
+    /// something in it
+    fn untitled_buffer_carry_over(&self, buffer_id: BufferId) -> Option<UntitledBuffer> {
+        let state = self.buffers.get(&buffer_id)?;
+        let metadata = self.buffer_metadata.get(&buffer_id);
+        let untitled = state.buffer.file_path().is_none()
+            && !metadata.is_some_and(|m| m.is_virtual())
+            && !self.is_terminal_buffer(buffer_id)
+            && self.stdin_streaming.as_ref().map(|s| s.buffer_id) != Some(buffer_id);
+        if !untitled || (state.buffer.is_empty() && !state.buffer.is_modified()) {
+            return None;
+        }
+        let Some(content) = state.buffer.to_string() else {
+            tracing::warn!("Can't carry untitled buffer {:?} over", buffer_id);
+            return None;
+        };
+        let cursor = state.cursors.primary();
+        Some(UntitledBuffer {
+            content,
+            modified: state.buffer.is_modified(),
+            display_name: metadata.map(|m| m.display_name.clone()),
+            pinned: metadata.is_some_and(|m| m.pinned),
+            cursor: cursor.position,
+            anchor: cursor.anchor,
+            active: buffer_id == self.active_buffer(),
+        })
+    }
+
+    /// Whether `buffer_id` is an empty, unmodified untitled buffer, like the
+    /// one a new editor starts with
+    fn is_blank_untitled_buffer(&self, buffer_id: BufferId) -> bool {
+        self.buffers.get(&buffer_id).is_some_and(|state| {
+            state.buffer.is_empty()
+                && !state.buffer.is_modified()
+                && state.buffer.file_path().is_none()
+                && !self.is_terminal_buffer(buffer_id)
+                && !self
+                    .buffer_metadata
+                    .get(&buffer_id)
+                    .is_some_and(|m| m.is_virtual())
+        })
+    }
+
+    /// Create a carried untitled buffer, in place of the blank one a new
+    /// editor starts with if that is showing
+    fn restore_untitled_buffer(&mut self, buffer: UntitledBuffer) -> BufferId {
+        let blank = self.active_buffer();
+        let buffer_id = if self.is_blank_untitled_buffer(blank) {
+            blank
+        } else {
+            self.new_buffer()
+        };
+
+        if let Some(state) = self.buffers.get_mut(&buffer_id) {
+            if let Some(name) = &buffer.display_name {
+                state.set_language_from_name(name, &self.grammar_registry);
+            }
+            if !buffer.content.is_empty() {
+                state.buffer.insert(0, &buffer.content);
+            }
+            state.buffer.set_modified(buffer.modified);
+            let len = state.buffer.len();
+            let cursor = state.cursors.primary_mut();
+            cursor.position = buffer.cursor.min(len);
+            cursor.anchor = buffer.anchor.map(|anchor| anchor.min(len));
+        }
+        if let Some(name) = buffer.display_name {
+            let mut metadata = BufferMetadata::new_unnamed(name);
+            metadata.pinned = buffer.pinned;
+            self.buffer_metadata.insert(buffer_id, metadata);
+        }
+        buffer_id
+    }
+}
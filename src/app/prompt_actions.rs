@@ -4,14 +4,17 @@
 
 use rust_i18n::t;
 
+use super::goto_offset::{classify_goto_input, GotoInput};
 use super::normalize_path;
 use super::BufferId;
 use super::BufferMetadata;
 use super::Editor;
 use crate::input::keybindings::Action;
+use crate::services::file_provider::is_remote_path;
+use crate::services::lsp::manager::{label_language, label_server};
 use crate::services::plugins::hooks::HookArgs;
 use crate::view::prompt::PromptType;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 /// Result of handling a prompt confirmation.
 pub enum PromptResult {
@@ -19,6 +22,9 @@ pub enum PromptResult {
     Done,
     /// Prompt handled, should execute this action next
     ExecuteAction(Action),
+    /// Prompt handled, should execute this action next with these values
+    /// of its command's parameters
+    ExecuteActionWithArgs(Action, serde_json::Map<String, serde_json::Value>),
     /// Prompt handled, should return early from handle_action
     EarlyReturn,
 }
@@ -36,7 +42,9 @@ impl Editor {
         match prompt_type {
             PromptType::OpenFile => {
                 let input_path = Path::new(&input);
-                let resolved_path = if input_path.is_absolute() {
+                let resolved_path = if is_remote_path(input_path) {
+                    input_path.to_path_buf()
+                } else if input_path.is_absolute() {
                     normalize_path(input_path)
                 } else {
                     normalize_path(&self.working_dir.join(input_path))
@@ -52,24 +60,18 @@ impl Editor {
                     );
                 }
             }
-            PromptType::SwitchProject => {
+            PromptType::InsertFile => {
                 let input_path = Path::new(&input);
                 let resolved_path = if input_path.is_absolute() {
                     normalize_path(input_path)
                 } else {
                     normalize_path(&self.working_dir.join(input_path))
                 };
-
-                if resolved_path.is_dir() {
+                self.insert_file(resolved_path, false);
+            }
+            PromptType::SwitchProject => {
+                if let Some(resolved_path) = self.resolve_project_dir(Path::new(&input)) {
                     self.change_working_dir(resolved_path);
-                } else {
-                    self.set_status_message(
-                        t!(
-                            "file.not_directory",
-                            path = resolved_path.display().to_string()
-                        )
-                        .to_string(),
-                    );
                 }
             }
             PromptType::SaveFileAs => {
@@ -111,8 +113,14 @@ impl Editor {
                 }
             }
             PromptType::Command => {
+                if let Some(line) = input.strip_prefix(':') {
+                    return self.run_command_line(line);
+                }
                 let commands = self.command_registry.read().unwrap().get_all();
                 if let Some(cmd) = commands.iter().find(|c| c.get_localized_name() == input) {
+                    if self.start_command_args(cmd) {
+                        return PromptResult::Done;
+                    }
                     let action = cmd.action.clone();
                     let cmd_name = cmd.get_localized_name();
                     self.set_status_message(t!("error.executing", cmd = &cmd_name).to_string());
@@ -127,18 +135,27 @@ impl Editor {
                     );
                 }
             }
-            PromptType::GotoLine => match input.trim().parse::<usize>() {
-                Ok(line_num) if line_num > 0 => {
-                    self.goto_line_col(line_num, None);
-                    self.set_status_message(t!("goto.jumped", line = line_num).to_string());
-                }
-                Ok(_) => {
-                    self.set_status_message(t!("goto.line_must_be_positive").to_string());
-                }
-                Err(_) => {
-                    self.set_status_message(t!("error.invalid_line", input = &input).to_string());
-                }
+            PromptType::GotoLine => match classify_goto_input(&input) {
+                GotoInput::Byte(offset) => self.handle_goto_byte_input(offset),
+                GotoInput::Percentage(percent) => self.handle_goto_percentage_input(percent),
+                GotoInput::Line(line) => match line.parse::<usize>() {
+                    Ok(line_num) if line_num > 0 => {
+                        self.goto_line_col(line_num, None);
+                        self.set_status_message(t!("goto.jumped", line = line_num).to_string());
+                    }
+                    Ok(_) => {
+                        self.set_status_message(t!("goto.line_must_be_positive").to_string());
+                    }
+                    Err(_) => {
+                        self.set_status_message(
+                            t!("error.invalid_line", input = &input).to_string(),
+                        );
+                    }
+                },
             },
+            PromptType::GotoByteOffset => self.handle_goto_byte_input(&input),
+            PromptType::GotoPercentage => self.handle_goto_percentage_input(&input),
+            PromptType::GotoDataPath => self.handle_goto_data_path_input(&input),
             PromptType::SetBackgroundFile => {
                 if let Err(e) = self.load_ansi_background(&input) {
                     self.set_status_message(
@@ -208,10 +225,16 @@ impl Editor {
                     self.set_status_message(t!("buffer.revert_cancelled").to_string());
                 }
             }
+            PromptType::ConfirmReloadChanged { buffer_id } => {
+                self.handle_confirm_reload(&input, buffer_id);
+            }
+            PromptType::ConfirmRevertToSaved { buffer_id, preview } => {
+                self.handle_confirm_revert_to_saved(&input, buffer_id, preview);
+            }
             PromptType::ConfirmSaveConflict => {
                 let input_lower = input.trim().to_lowercase();
                 if input_lower == "o" || input_lower == "overwrite" {
-                    if let Err(e) = self.save() {
+                    if let Err(e) = self.save_with_format() {
                         self.set_status_message(
                             t!("file.save_failed", error = e.to_string()).to_string(),
                         );
@@ -220,6 +243,18 @@ impl Editor {
                     self.set_status_message(t!("buffer.save_cancelled").to_string());
                 }
             }
+            PromptType::ConfirmSudoSave { buffer_id } => {
+                self.handle_confirm_sudo_save(&input, buffer_id);
+            }
+            PromptType::ConfirmUnlockProtected { buffer_id } => {
+                self.handle_confirm_unlock_protected(&input, buffer_id);
+            }
+            PromptType::ConfirmProtectedSave { buffer_id } => {
+                self.handle_confirm_protected_save(&input, buffer_id);
+            }
+            PromptType::ConfirmCreateFile { path } => {
+                self.handle_confirm_create_file(&input, path);
+            }
             PromptType::ConfirmOverwriteFile { path } => {
                 let input_lower = input.trim().to_lowercase();
                 if input_lower == "o" || input_lower == "overwrite" {
@@ -228,6 +263,14 @@ impl Editor {
                     self.set_status_message(t!("buffer.save_cancelled").to_string());
                 }
             }
+            PromptType::ConfirmInsertLargeFile { path } => {
+                let input_lower = input.trim().to_lowercase();
+                if input_lower == "i" || input_lower == "insert" {
+                    self.insert_file(path, true);
+                } else {
+                    self.set_status_message(t!("file.insert_cancelled").to_string());
+                }
+            }
             PromptType::ConfirmCloseBuffer { buffer_id } => {
                 if self.handle_confirm_close_buffer(&input, buffer_id) {
                     return PromptResult::EarlyReturn;
@@ -242,6 +285,47 @@ impl Editor {
                     self.set_status_message(t!("buffer.close_cancelled").to_string());
                 }
             }
+            PromptType::ConfirmQuitWithSavesRunning => {
+                let input_lower = input.trim().to_lowercase();
+                let discard_key = t!("prompt.key.discard").to_string().to_lowercase();
+                if input_lower == discard_key || input_lower == "discard" {
+                    self.should_quit = true;
+                } else {
+                    self.set_status_message(t!("file.quit_cancelled_saving").to_string());
+                }
+            }
+            PromptType::RestoreSessionSnapshot => {
+                self.restore_session_snapshot(PathBuf::from(input.trim()));
+            }
+            PromptType::ConfirmRestoreSessionSnapshot { path } => {
+                let input_lower = input.trim().to_lowercase();
+                let discard_key = t!("prompt.key.discard").to_string().to_lowercase();
+                if input_lower == discard_key || input_lower == "discard" {
+                    self.apply_session_snapshot(&path);
+                } else {
+                    self.set_status_message(t!("session.snapshot_cancelled").to_string());
+                }
+            }
+            PromptType::SaveLayoutAs => {
+                self.save_layout_as(&input);
+            }
+            PromptType::SwitchLayout => {
+                self.switch_layout(&input);
+            }
+            PromptType::DeleteLayout => {
+                self.delete_layout(&input);
+            }
+            PromptType::OpenRecentProject => {
+                self.open_recent_project(&input);
+            }
+            PromptType::ConfirmRemoveRecentProject { path } => {
+                let input_lower = input.trim().to_lowercase();
+                if input_lower == "y" || input_lower == "yes" {
+                    self.remove_recent_project(&path);
+                } else {
+                    self.set_status_message(t!("project.recent_remove_cancelled").to_string());
+                }
+            }
             PromptType::LspRename {
                 original_text,
                 start_pos,
@@ -287,6 +371,52 @@ impl Editor {
                     self.switch_to_tab(BufferId(id));
                 }
             }
+            PromptType::CompareBufferWith => {
+                self.handle_compare_buffer_prompt(&input);
+            }
+            PromptType::CommandArgument {
+                command,
+                index,
+                values,
+            } => {
+                return self.handle_command_arg_input(
+                    &command,
+                    index,
+                    values,
+                    input,
+                    selected_index,
+                );
+            }
+            PromptType::CompareDirectoriesLeft => {
+                self.handle_compare_directories_left(&input);
+            }
+            PromptType::CompareDirectoriesRight { left } => {
+                self.handle_compare_directories_right(left, &input);
+            }
+            PromptType::BatchCodeActionKind => {
+                self.handle_batch_code_action_kind(&input, selected_index);
+            }
+            PromptType::BatchCodeActionScope { kind } => {
+                self.handle_batch_code_action_scope(kind, &input, selected_index);
+            }
+            PromptType::PasteFromHistory => {
+                self.paste_from_history(&input);
+            }
+            PromptType::KeyboardShortcutsFilter => {
+                self.filter_keyboard_shortcuts(&input);
+            }
+            PromptType::ExportUsageStats => {
+                self.export_usage_stats(&input);
+            }
+            PromptType::ExportSettingsArchive => {
+                self.export_settings_archive(&input);
+            }
+            PromptType::ImportSettingsArchive => {
+                self.preview_settings_import(&input);
+            }
+            PromptType::EditorLogLevel => {
+                self.set_editor_log_level(&input);
+            }
             PromptType::QueryReplaceConfirm => {
                 // This is handled by InsertChar, not PromptConfirm
                 // But if somehow Enter is pressed, treat it as skip (n)
@@ -303,6 +433,21 @@ impl Editor {
             PromptType::ShellCommand { replace } => {
                 self.handle_shell_command(&input, replace);
             }
+            PromptType::InsertCommandOutput { selection_as_stdin } => {
+                self.run_insert_command_output(&input, selection_as_stdin);
+            }
+            PromptType::SortLines => {
+                self.handle_sort_lines_prompt(&input);
+            }
+            PromptType::ConfirmTransformBuffer { transform } => {
+                self.handle_confirm_transform_buffer(&input, transform);
+            }
+            PromptType::AlignSelections => {
+                self.align_selections(&input);
+            }
+            PromptType::CompactBuffers { all } => {
+                self.handle_compact_buffers_prompt(&input, all);
+            }
         }
         PromptResult::Done
     }
@@ -351,6 +496,8 @@ impl Editor {
             before_len
         );
 
+        let gitattributes_note =
+            self.apply_gitattributes_for_save(self.active_buffer(), &full_path);
         match self.active_state_mut().buffer.save_to_file(&full_path) {
             Ok(()) => {
                 let after_save_idx = self.active_event_log().current_index();
@@ -361,8 +508,20 @@ impl Editor {
                     after_save_len
                 );
 
+                // The buffer was locked for its old file, which couldn't be written
+                // or was protected
+                let buffer_id = self.active_buffer();
+                if self
+                    .buffer_metadata
+                    .get(&buffer_id)
+                    .is_some_and(|m| m.file_locked())
+                {
+                    self.active_state_mut().editing_disabled = false;
+                }
                 let metadata = BufferMetadata::with_file(full_path.clone(), &self.working_dir);
-                self.buffer_metadata.insert(self.active_buffer(), metadata);
+                self.buffer_metadata.insert(buffer_id, metadata);
+                self.refresh_file_writability(buffer_id);
+                self.refresh_file_protection(buffer_id);
 
                 self.active_event_log_mut().mark_saved();
                 tracing::debug!(
@@ -377,7 +536,7 @@ impl Editor {
                     }
                 }
 
-                self.notify_lsp_save();
+                self.notify_lsp_save(self.active_buffer());
 
                 self.emit_event(
                     crate::model::control_event::events::FILE_SAVED.name,
@@ -400,6 +559,8 @@ impl Editor {
                     } else {
                         self.set_status_message(t!("buffer.saved_and_closed").to_string());
                     }
+                } else if let Some(note) = gitattributes_note {
+                    self.set_status_message(note);
                 } else {
                     self.set_status_message(
                         t!("file.saved_as", path = full_path.display().to_string()).to_string(),
@@ -574,6 +735,9 @@ impl Editor {
     }
 
     /// Handle StopLspServer prompt confirmation.
+    ///
+    /// The input is a language, stopping all its servers, or the label of
+    /// one of its several servers.
     fn handle_stop_lsp_server(&mut self, input: &str) {
         let language = input.trim();
         if language.is_empty() {
@@ -582,8 +746,13 @@ impl Editor {
 
         if let Some(lsp) = &mut self.lsp {
             if lsp.shutdown_server(language) {
-                if let Some(lsp_config) = self.config.lsp.get_mut(language) {
-                    lsp_config.auto_start = false;
+                let server = label_server(language);
+                if let Some(lsp_config) = self.config.lsp.get_mut(label_language(language)) {
+                    for server_config in lsp_config.servers_mut() {
+                        if server.is_none_or(|name| server_config.server_name() == name) {
+                            server_config.auto_start = false;
+                        }
+                    }
                     if let Err(e) = self.save_config() {
                         tracing::warn!(
                             "Failed to save config after disabling LSP auto-start: {}",
@@ -0,0 +1,148 @@
+//! Paragraph and sentence motions
+//!
+//! Unlike the other cursor actions these depend on the editor's config (the
+//! abbreviations that don't end a sentence) and on the buffer's language
+//! (markdown headings and list items are paragraphs of their own), so they
+//! are turned into events here rather than in `convert_action_to_events`.
+
+use std::ops::Range;
+
+use super::Editor;
+use crate::input::keybindings::Action;
+use crate::model::buffer::Buffer;
+use crate::model::event::Event;
+use crate::primitives::prose_navigation::{self, ProseOptions};
+use crate::services::lsp::manager::detect_language;
+
+type Motion = fn(&mut Buffer, usize, &ProseOptions) -> usize;
+type Deletion = fn(&mut Buffer, usize, &ProseOptions) -> Range<usize>;
+
+#[derive(Clone, Copy)]
+enum ProseAction {
+    Move(Motion),
+    Select(Motion),
+    SelectSentence,
+    Delete(Deletion),
+}
+
+impl ProseAction {
+    fn from_action(action: &Action) -> Option<Self> {
+        Some(match action {
+            Action::MoveParagraphForward => Self::Move(prose_navigation::paragraph_forward),
+            Action::MoveParagraphBackward => Self::Move(prose_navigation::paragraph_backward),
+            Action::MoveSentenceForward => Self::Move(prose_navigation::sentence_forward),
+            Action::MoveSentenceBackward => Self::Move(prose_navigation::sentence_backward),
+            Action::SelectParagraphForward => Self::Select(prose_navigation::paragraph_forward),
+            Action::SelectParagraphBackward => Self::Select(prose_navigation::paragraph_backward),
+            Action::SelectSentenceForward => Self::Select(prose_navigation::sentence_forward),
+            Action::SelectSentenceBackward => Self::Select(prose_navigation::sentence_backward),
+            Action::SelectSentence => Self::SelectSentence,
+            Action::DeleteParagraphForward => {
+                Self::Delete(prose_navigation::paragraph_delete_forward)
+            }
+            Action::DeleteParagraphBackward => {
+                Self::Delete(prose_navigation::paragraph_delete_backward)
+            }
+            Action::DeleteSentenceForward => {
+                Self::Delete(prose_navigation::sentence_delete_forward)
+            }
+            Action::DeleteSentenceBackward => {
+                Self::Delete(prose_navigation::sentence_delete_backward)
+            }
+            _ => return None,
+        })
+    }
+}
+
+impl Editor {
+    /// Events of a paragraph or sentence action for every cursor of the
+    /// active buffer, or None when `action` isn't one
+    pub(super) fn prose_action_events(&mut self, action: &Action) -> Option<Vec<Event>> {
+        let prose_action = ProseAction::from_action(action)?;
+        let markdown = self
+            .buffer_metadata
+            .get(&self.active_buffer())
+            .and_then(|metadata| metadata.file_path())
+            .and_then(|path| detect_language(path, &self.config.languages))
+            .is_some_and(|language| language == "markdown");
+        let abbreviations = self.config.editor.sentence_abbreviations.clone();
+        let options = ProseOptions {
+            markdown,
+            abbreviations: &abbreviations,
+            estimated_line_length: self.config.editor.estimated_line_length,
+        };
+
+        let state = self.active_state_mut();
+        let mut events = Vec::new();
+        let mut deletions = Vec::new();
+        for (cursor_id, cursor) in state.cursors.iter() {
+            match prose_action {
+                ProseAction::Move(motion) => {
+                    let new_position = motion(&mut state.buffer, cursor.position, &options);
+                    // Preserve anchor if deselect_on_move is false (Emacs mark mode)
+                    let new_anchor = if cursor.deselect_on_move {
+                        None
+                    } else {
+                        cursor.anchor
+                    };
+                    events.push(Event::MoveCursor {
+                        cursor_id,
+                        old_position: cursor.position,
+                        new_position,
+                        old_anchor: cursor.anchor,
+                        new_anchor,
+                        old_sticky_column: cursor.sticky_column,
+                        new_sticky_column: 0,
+                    });
+                }
+                ProseAction::Select(motion) => {
+                    let new_position = motion(&mut state.buffer, cursor.position, &options);
+                    events.push(Event::MoveCursor {
+                        cursor_id,
+                        old_position: cursor.position,
+                        new_position,
+                        old_anchor: cursor.anchor,
+                        new_anchor: Some(cursor.anchor.unwrap_or(cursor.position)),
+                        old_sticky_column: cursor.sticky_column,
+                        new_sticky_column: 0,
+                    });
+                }
+                ProseAction::SelectSentence => {
+                    let range = prose_navigation::sentence_range(
+                        &mut state.buffer,
+                        cursor.position,
+                        &options,
+                    );
+                    if let Some(range) = range {
+                        events.push(Event::MoveCursor {
+                            cursor_id,
+                            old_position: cursor.position,
+                            new_position: range.end,
+                            old_anchor: cursor.anchor,
+                            new_anchor: Some(range.start),
+                            old_sticky_column: cursor.sticky_column,
+                            new_sticky_column: 0,
+                        });
+                    }
+                }
+                ProseAction::Delete(deletion) => {
+                    let range = cursor
+                        .selection_range()
+                        .unwrap_or_else(|| deletion(&mut state.buffer, cursor.position, &options));
+                    if !range.is_empty() {
+                        deletions.push((cursor_id, range));
+                    }
+                }
+            }
+        }
+        for (cursor_id, range) in deletions {
+            let deleted_text = state.get_text_range(range.start, range.end);
+            events.push(Event::Delete {
+                range,
+                deleted_text,
+                cursor_id,
+            });
+        }
+        Some(events)
+    }
+}
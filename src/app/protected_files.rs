@@ -0,0 +1,392 @@
+//! Files matching `files.protected_globs`
+//!
+//! A file whose canonical path matches one of the globs (gitignore syntax,
+//! rooted at the file system root, later globs winning) opens read-only and
+//! shows "[protected]" in the status bar. "Unlock Protected File" names the
+//! glob and asks before edits are accepted; the first save after that asks
+//! once more, with the number of lines added and removed. Plugin edits to a
+//! protected buffer that wasn't unlocked are refused and reported through
+//! the `edit_rejected` hook.
+
+use std::path::{Path, PathBuf};
+
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use rust_i18n::t;
+
+use super::types::ProtectedFile;
+use super::Editor;
+use crate::input::keybindings::Action;
+use crate::model::event::BufferId;
+use crate::model::line_diff::matching_lines;
+use crate::services::file_provider::is_remote_path;
+use crate::services::plugins::hooks::HookArgs;
+use crate::view::prompt::PromptType;
+
+/// Matcher of the protected globs, rooted at the file system root
+fn protected_matcher(patterns: &[String]) -> Option<Gitignore> {
+    if patterns.is_empty() {
+        return None;
+    }
+    let mut builder = GitignoreBuilder::new("/");
+    for pattern in patterns {
+        if let Err(e) = builder.add_line(None, pattern) {
+            tracing::warn!("Invalid protected glob '{}': {}", pattern, e);
+        }
+    }
+    match builder.build() {
+        Ok(matcher) => Some(matcher),
+        Err(e) => {
+            tracing::warn!("Failed to build protected globs: {}", e);
+            None
+        }
+    }
+}
+
+/// The glob protecting `path`, if one does
+///
+/// The last glob matching the path or one of its directories decides, so a
+/// "!" glob lifts the protection of the globs before it.
+fn protecting_rule(matcher: &Gitignore, path: &Path) -> Option<String> {
+    let matched = if path.starts_with(matcher.path()) {
+        matcher.matched_path_or_any_parents(path, false)
+    } else {
+        matcher.matched(path, false)
+    };
+    if !matched.is_ignore() {
+        return None;
+    }
+    matched.inner().map(|glob| glob.original().to_string())
+}
+
+/// The path the globs are matched against: the canonical one or, for a file
+/// that doesn't exist yet, the canonical directory joined with its name
+fn canonical_path(path: &Path) -> PathBuf {
+    if let Ok(canonical) = path.canonicalize() {
+        return canonical;
+    }
+    match (path.parent(), path.file_name()) {
+        (Some(parent), Some(name)) => parent
+            .canonicalize()
+            .map(|parent| parent.join(name))
+            .unwrap_or_else(|_| path.to_path_buf()),
+        _ => path.to_path_buf(),
+    }
+}
+
+/// Lines added and removed going from `old` to `new`
+fn changed_lines(old: &[u8], new: &[u8]) -> (usize, usize) {
+    let lines = |text: &[u8]| text.split(|&b| b == b'\n').count();
+    let unchanged = matching_lines(old, new).len();
+    (lines(new) - unchanged, lines(old) - unchanged)
+}
+
+impl Editor {
+    /// Check a buffer's file against `files.protected_globs`, and lock or
+    /// release the buffer when that changed
+    pub(super) fn refresh_file_protection(&mut self, buffer_id: BufferId) {
+        let matcher = protected_matcher(&self.config.files.protected_globs);
+        self.refresh_file_protection_with(buffer_id, matcher.as_ref());
+    }
+
+    /// Check every open file again after the protected globs may have
+    /// changed
+    pub(super) fn apply_protected_globs(&mut self) {
+        let matcher = protected_matcher(&self.config.files.protected_globs);
+        let buffer_ids: Vec<BufferId> = self.buffer_metadata.keys().copied().collect();
+        for buffer_id in buffer_ids {
+            self.refresh_file_protection_with(buffer_id, matcher.as_ref());
+        }
+    }
+
+    /// A file still protected, by the same glob or another, stays unlocked
+    /// if it was
+    fn refresh_file_protection_with(&mut self, buffer_id: BufferId, matcher: Option<&Gitignore>) {
+        let Some(metadata) = self.buffer_metadata.get(&buffer_id) else {
+            return;
+        };
+        // Binary buffers are read-only anyway
+        if metadata.binary || metadata.is_virtual() {
+            return;
+        }
+        let rule = metadata
+            .file_path()
+            .filter(|path| !path.as_os_str().is_empty() && !is_remote_path(path))
+            .zip(matcher)
+            .and_then(|(path, matcher)| protecting_rule(matcher, &canonical_path(path)));
+        if metadata.protected.as_ref().map(|p| &p.rule) == rule.as_ref() {
+            return;
+        }
+
+        let newly_protected = metadata.protected.is_none();
+        let name = metadata.display_name.clone();
+        let protected = rule.map(|rule| match &metadata.protected {
+            Some(previous) => ProtectedFile {
+                rule,
+                ..previous.clone()
+            },
+            None => ProtectedFile {
+                rule,
+                unlocked: false,
+                save_confirmed: false,
+            },
+        });
+        if let Some(protected) = protected.as_ref().filter(|_| newly_protected) {
+            tracing::info!("{} is protected by '{}'", name, protected.rule);
+            self.set_status_message(
+                t!(
+                    "protected.opened",
+                    name = name,
+                    rule = protected.rule.as_str()
+                )
+                .to_string(),
+            );
+        }
+        self.update_file_lock(buffer_id, |metadata| metadata.protected = protected);
+    }
+
+    /// Whether the active buffer's file is protected and wasn't unlocked
+    pub fn is_active_file_protected(&self) -> bool {
+        self.buffer_metadata
+            .get(&self.active_buffer())
+            .is_some_and(|metadata| metadata.protected_locked())
+    }
+
+    /// Ask to unlock the active buffer's protected file for editing
+    pub fn unlock_protected_file(&mut self) {
+        let buffer_id = self.active_buffer();
+        let Some(metadata) = self.buffer_metadata.get(&buffer_id) else {
+            return;
+        };
+        let name = metadata.display_name.clone();
+        match &metadata.protected {
+            None => {
+                self.set_status_message(t!("protected.not_protected", name = name).to_string());
+            }
+            Some(protected) if protected.unlocked => {
+                self.set_status_message(t!("protected.already_unlocked", name = name).to_string());
+            }
+            Some(protected) => {
+                let prompt = t!(
+                    "protected.unlock_confirm",
+                    name = name,
+                    rule = protected.rule.as_str()
+                )
+                .to_string();
+                self.start_prompt(prompt, PromptType::ConfirmUnlockProtected { buffer_id });
+            }
+        }
+    }
+
+    /// Answer to "Unlock Protected File"
+    pub(super) fn handle_confirm_unlock_protected(&mut self, input: &str, buffer_id: BufferId) {
+        let input_lower = input.trim().to_lowercase();
+        if input_lower != "y" && input_lower != "yes" {
+            self.set_status_message(t!("protected.unlock_cancelled").to_string());
+            return;
+        }
+        let Some(metadata) = self.buffer_metadata.get(&buffer_id) else {
+            return;
+        };
+        let Some(protected) = metadata.protected.as_ref().filter(|p| !p.unlocked) else {
+            return;
+        };
+        let name = metadata.display_name.clone();
+        let path = metadata.file_path().cloned().unwrap_or_default();
+        tracing::info!(
+            "Unlocked protected file {} (rule '{}')",
+            path.display(),
+            protected.rule
+        );
+        self.update_file_lock(buffer_id, |metadata| {
+            if let Some(protected) = metadata.protected.as_mut() {
+                protected.unlocked = true;
+                protected.save_confirmed = false;
+            }
+        });
+        self.set_status_message(t!("protected.unlocked", name = name).to_string());
+    }
+
+    /// Ask before the first save of a protected file since it was unlocked,
+    /// showing how many lines change
+    ///
+    /// Returns true when the save has to wait: for the answer, or because
+    /// the file is still locked.
+    pub(super) fn ask_before_protected_save(&mut self, buffer_id: BufferId) -> bool {
+        let Some(metadata) = self.buffer_metadata.get(&buffer_id) else {
+            return false;
+        };
+        let Some(protected) = &metadata.protected else {
+            return false;
+        };
+        if protected.save_confirmed {
+            return false;
+        }
+        let name = metadata.display_name.clone();
+        if !protected.unlocked {
+            self.set_status_message(t!("protected.save_locked", name = name).to_string());
+            return true;
+        }
+
+        let new = self
+            .buffers
+            .get(&buffer_id)
+            .and_then(|state| state.buffer.get_all_text());
+        let prompt = match new {
+            Some(new) => {
+                let old = metadata
+                    .file_path()
+                    .and_then(|path| std::fs::read(path).ok())
+                    .unwrap_or_default();
+                let (added, removed) = changed_lines(&old, &new);
+                t!(
+                    "protected.save_confirm",
+                    name = name,
+                    added = added,
+                    removed = removed
+                )
+            }
+            // Not all of it is loaded, so there is nothing to count
+            None => t!("protected.save_confirm_unknown", name = name),
+        };
+        self.start_prompt(
+            prompt.to_string(),
+            PromptType::ConfirmProtectedSave { buffer_id },
+        );
+        true
+    }
+
+    /// Answer to the confirmation of saving a protected file
+    pub(super) fn handle_confirm_protected_save(&mut self, input: &str, buffer_id: BufferId) {
+        let input_lower = input.trim().to_lowercase();
+        if input_lower != "y" && input_lower != "yes" {
+            self.set_status_message(t!("buffer.save_cancelled").to_string());
+            return;
+        }
+        let Some(protected) = self
+            .buffer_metadata
+            .get_mut(&buffer_id)
+            .and_then(|metadata| metadata.protected.as_mut())
+        else {
+            return;
+        };
+        protected.save_confirmed = true;
+        if self.active_buffer() != buffer_id {
+            return;
+        }
+        if let Err(e) = self.handle_action(Action::Save) {
+            self.set_status_message(t!("file.save_failed", error = e.to_string()).to_string());
+        }
+    }
+
+    /// Whether a save of a buffer is refused because its file is protected
+    /// and saving wasn't confirmed since it was unlocked
+    pub(super) fn protected_save_refused(&self, buffer_id: BufferId) -> bool {
+        self.buffer_metadata
+            .get(&buffer_id)
+            .and_then(|metadata| metadata.protected.as_ref())
+            .is_some_and(|protected| !protected.save_confirmed)
+    }
+
+    /// Refuse a plugin edit to a protected buffer that wasn't unlocked,
+    /// telling plugins through the `edit_rejected` hook
+    ///
+    /// `command` names the edit, e.g. "insert_text". Returns true when the
+    /// edit is refused.
+    pub(super) fn reject_protected_plugin_edit(
+        &mut self,
+        buffer_id: BufferId,
+        command: &str,
+    ) -> bool {
+        let Some(rule) = self
+            .buffer_metadata
+            .get(&buffer_id)
+            .filter(|metadata| metadata.protected_locked())
+            .and_then(|metadata| metadata.protected.as_ref())
+            .map(|protected| protected.rule.clone())
+        else {
+            return false;
+        };
+        tracing::warn!(
+            "Refused plugin {} on protected buffer {:?} (rule '{}')",
+            command,
+            buffer_id,
+            rule
+        );
+        self.plugin_manager.run_hook(
+            "edit_rejected",
+            HookArgs::EditRejected {
+                buffer_id,
+                command: command.to_string(),
+                reason: "protected".to_string(),
+                rule,
+            },
+        );
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(patterns: &[&str], path: &str) -> Option<String> {
+        let patterns: Vec<String> = patterns.iter().map(|p| p.to_string()).collect();
+        let matcher = protected_matcher(&patterns).unwrap();
+        protecting_rule(&matcher, Path::new(path))
+    }
+
+    #[test]
+    fn test_default_globs() {
+        let defaults = crate::config::FilesConfig::default().protected_globs;
+        let defaults: Vec<&str> = defaults.iter().map(String::as_str).collect();
+        assert_eq!(
+            rule(&defaults, "/srv/app/.env.production").as_deref(),
+            Some("**/.env*")
+        );
+        assert_eq!(
+            rule(&defaults, "/home/me/.ssh/id_ed25519.pub").as_deref(),
+            Some("**/id_ed25519*")
+        );
+        assert_eq!(rule(&defaults, "/etc/hosts").as_deref(), Some("/etc/**"));
+        assert_eq!(rule(&defaults, "/usr/home/me/notes.txt"), None);
+        assert_eq!(
+            rule(&defaults, "/usr/home/me/.env").as_deref(),
+            Some("**/.env*")
+        );
+        assert_eq!(rule(&defaults, "/srv/app/.env.example"), None);
+        assert_eq!(rule(&defaults, "/srv/app/main.rs"), None);
+        // Anchored globs only match from the root
+        assert_eq!(rule(&defaults, "/home/me/etc/notes.txt"), None);
+    }
+
+    #[test]
+    fn test_later_globs_win() {
+        // A later negation lifts the protection, a later glob puts it back
+        let patterns = ["**/.env*", "!**/.env.local", "/srv/**/.env.local"];
+        assert_eq!(
+            rule(&patterns, "/srv/app/.env.local").as_deref(),
+            Some("/srv/**/.env.local")
+        );
+        assert_eq!(rule(&patterns, "/home/me/.env.local"), None);
+        assert_eq!(
+            rule(&patterns, "/home/me/.env").as_deref(),
+            Some("**/.env*")
+        );
+    }
+
+    #[test]
+    fn test_directory_globs_protect_their_files() {
+        let patterns = ["secrets/", "!/srv/public/secrets/"];
+        assert_eq!(
+            rule(&patterns, "/srv/app/secrets/db.key").as_deref(),
+            Some("secrets/")
+        );
+        assert_eq!(rule(&patterns, "/srv/public/secrets/readme.md"), None);
+    }
+
+    #[test]
+    fn test_changed_lines() {
+        assert_eq!(changed_lines(b"a\nb\nc\n", b"a\nB\nc\nd\n"), (2, 1));
+        assert_eq!(changed_lines(b"same\n", b"same\n"), (0, 0));
+    }
+}
@@ -0,0 +1,194 @@
+//! Recent projects
+//!
+//! "Open Recent Project" lists the directories from [`RecentProjects`] with
+//! their full path and when they were last opened, pinned ones first.
+//! Choosing one switches to it through [`Editor::change_working_dir`], so the
+//! restart saves this project's session and restores the chosen one's. The
+//! last entry opens the folder browser instead. Projects whose directory is
+//! gone are greyed out; choosing one offers to drop it from the list.
+
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use rust_i18n::t;
+
+use super::session_snapshots::format_snapshot_time;
+use super::Editor;
+use crate::input::commands::Suggestion;
+use crate::services::recent_projects::{RecentProject, RecentProjects};
+use crate::view::prompt::{Prompt, PromptType};
+
+/// Picker value of the "Open Directory…" entry; project paths are never empty
+const OPEN_DIRECTORY: &str = "";
+
+impl Editor {
+    /// Load the recent projects, picking up ones other instances recorded
+    fn load_recent_projects(&mut self) -> &RecentProjects {
+        self.recent_projects = RecentProjects::load(&self.dir_context.recent_projects_path());
+        &self.recent_projects
+    }
+
+    /// Write the recent projects, reporting a failure
+    fn save_recent_projects(&mut self) -> bool {
+        match self
+            .recent_projects
+            .save(&self.dir_context.recent_projects_path())
+        {
+            Ok(()) => true,
+            Err(e) => {
+                self.set_status_message(
+                    t!("project.recent_save_failed", error = e.to_string()).to_string(),
+                );
+                false
+            }
+        }
+    }
+
+    /// Move the working directory to the front of the recent projects
+    pub fn record_recent_project(&mut self) {
+        let dir = self.working_dir.clone();
+        self.record_recent_project_dir(&dir);
+    }
+
+    /// Move `dir` to the front of the recent projects
+    pub(super) fn record_recent_project_dir(&mut self, dir: &Path) {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        self.load_recent_projects();
+        self.recent_projects.record(dir, now);
+        if let Err(e) = self
+            .recent_projects
+            .save(&self.dir_context.recent_projects_path())
+        {
+            tracing::warn!("Failed to save recent projects: {}", e);
+        }
+    }
+
+    /// Show a picker of the recent projects other than the current one,
+    /// followed by an entry for the folder browser
+    pub fn start_recent_projects_prompt(&mut self) {
+        let current = self
+            .working_dir
+            .canonicalize()
+            .unwrap_or_else(|_| self.working_dir.clone());
+        let mut suggestions: Vec<Suggestion> = self
+            .load_recent_projects()
+            .ordered()
+            .into_iter()
+            .filter(|project| project.path != current)
+            .map(recent_project_suggestion)
+            .collect();
+        suggestions.push(Suggestion {
+            text: t!("project.open_directory").to_string(),
+            description: Some(t!("project.open_directory_desc").to_string()),
+            value: Some(OPEN_DIRECTORY.to_string()),
+            disabled: false,
+            keybinding: None,
+            source: None,
+        });
+
+        self.prompt = Some(Prompt::with_suggestions(
+            t!("project.recent_prompt").to_string(),
+            PromptType::OpenRecentProject,
+            suggestions,
+        ));
+        if let Some(prompt) = self.prompt.as_mut() {
+            prompt.selected_suggestion = Some(0);
+        }
+    }
+
+    /// Act on the picker entry `value`: switch to the project, offer to drop
+    /// it if its directory is gone, or open the folder browser
+    pub(super) fn open_recent_project(&mut self, value: &str) {
+        if value == OPEN_DIRECTORY {
+            self.start_prompt(
+                t!("file.switch_project_prompt").to_string(),
+                PromptType::SwitchProject,
+            );
+            self.init_folder_open_state();
+            return;
+        }
+        let path = PathBuf::from(value);
+        if !path.is_dir() && self.load_recent_projects().get(&path).is_some() {
+            self.start_prompt(
+                t!(
+                    "project.recent_missing_confirm",
+                    path = path.display().to_string()
+                )
+                .to_string(),
+                PromptType::ConfirmRemoveRecentProject { path },
+            );
+            return;
+        }
+        // Anything else typed in is taken as a directory to switch to
+        if let Some(dir) = self.resolve_project_dir(&path) {
+            self.change_working_dir(dir);
+        }
+    }
+
+    /// Drop `path` from the recent projects
+    pub(super) fn remove_recent_project(&mut self, path: &Path) {
+        self.load_recent_projects();
+        if !self.recent_projects.remove(path) {
+            return;
+        }
+        if self.save_recent_projects() {
+            self.set_status_message(
+                t!("project.recent_removed", path = path.display().to_string()).to_string(),
+            );
+        }
+    }
+
+    /// Pin the current project in the recent projects, or unpin it
+    pub fn toggle_pin_project(&mut self) {
+        let dir = self.working_dir.clone();
+        self.load_recent_projects();
+        if self.recent_projects.get(&dir).is_none() {
+            self.record_recent_project_dir(&dir);
+        }
+        let pinned = !self
+            .recent_projects
+            .get(&dir)
+            .is_some_and(|project| project.pinned);
+        self.recent_projects.set_pinned(&dir, pinned);
+        if self.save_recent_projects() {
+            let path = dir.display().to_string();
+            let message = if pinned {
+                t!("project.pinned", path = path)
+            } else {
+                t!("project.unpinned", path = path)
+            };
+            self.set_status_message(message.to_string());
+        }
+    }
+}
+
+/// Picker entry for a project: its directory name, with the full path and
+/// when it was last opened
+fn recent_project_suggestion(project: &RecentProject) -> Suggestion {
+    let name = project
+        .path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| project.path.display().to_string());
+    let path = project.path.display().to_string();
+    let time = format_snapshot_time(project.last_opened);
+    let missing = !project.path.is_dir();
+    let description = if missing {
+        t!("project.recent_entry_missing", path = path)
+    } else if project.pinned {
+        t!("project.recent_entry_pinned", path = path, time = time)
+    } else {
+        t!("project.recent_entry", path = path, time = time)
+    };
+    Suggestion {
+        text: name,
+        description: Some(description.to_string()),
+        value: Some(project.path.display().to_string()),
+        disabled: missing,
+        keybinding: None,
+        source: None,
+    }
+}
@@ -8,19 +8,42 @@
 //! - Cleaning up recovery files
 
 use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 
 use crate::model::event::BufferId;
+use crate::services::file_saver::FileSave;
+use crate::services::recovery::types::RecoveryChunk;
 
+use super::types::BufferMetadata;
 use super::Editor;
 
+/// How long deleting a buffer's recovery waits for a recovery write of it
+/// that is still running
+const RECOVERY_WRITE_TIMEOUT: Duration = Duration::from_secs(10);
+
 impl Editor {
     /// Start the recovery session (call on editor startup after recovery check)
+    ///
+    /// Only the instance owning the session writes recovery files.
     pub fn start_recovery_session(&mut self) -> io::Result<()> {
+        if !self.owns_session() {
+            return Ok(());
+        }
         self.recovery_service.start_session()
     }
 
     /// End the recovery session cleanly (call on normal shutdown)
+    ///
+    /// Once another instance has taken over the session, its recovery files
+    /// are left alone.
     pub fn end_recovery_session(&mut self) -> io::Result<()> {
+        for (_, mut write) in self.recovery_writes.drain() {
+            let _ = write.wait(RECOVERY_WRITE_TIMEOUT);
+        }
+        if !self.owns_session() {
+            return Ok(());
+        }
         self.recovery_service.end_session()
     }
 
@@ -29,6 +52,11 @@ impl Editor {
         self.recovery_service.should_offer_recovery()
     }
 
+    /// Directory holding this session's recovery files (the project's bucket)
+    pub fn recovery_dir(&self) -> &Path {
+        self.recovery_service.storage().base_dir()
+    }
+
     /// Get list of recoverable files
     pub fn list_recoverable_files(
         &self,
@@ -36,6 +64,31 @@ impl Editor {
         self.recovery_service.list_recoverable()
     }
 
+    /// Recovery ID and display name for a buffer.
+    ///
+    /// File-backed buffers are keyed by path. Untitled buffers get an ID that
+    /// stays the same for the buffer's lifetime, and a generated display name.
+    fn recovery_identity(
+        &mut self,
+        buffer_id: BufferId,
+        path: Option<&Path>,
+    ) -> (String, Option<String>) {
+        if path.is_some() {
+            return (self.recovery_service.get_buffer_id(path), None);
+        }
+        let recovery_id = self
+            .untitled_recovery_ids
+            .entry(buffer_id)
+            .or_insert_with(|| self.recovery_service.get_buffer_id(None))
+            .clone();
+        let name = self
+            .buffer_metadata
+            .get(&buffer_id)
+            .map(|metadata| metadata.display_name.clone())
+            .unwrap_or_else(|| format!("Untitled-{}", buffer_id.0));
+        (recovery_id, Some(name))
+    }
+
     /// Recover all buffers from recovery files
     /// Returns the number of buffers recovered
     pub fn recover_all_buffers(&mut self) -> io::Result<usize> {
@@ -68,12 +121,15 @@ impl Editor {
                         }
                     } else {
                         // Unsaved buffer - create new buffer with recovered content
-                        self.new_buffer();
+                        let buffer_id = self.new_buffer();
+                        let name = entry.metadata.display_name();
+                        self.buffer_metadata
+                            .insert(buffer_id, BufferMetadata::new_unnamed(name.clone()));
                         let state = self.active_state_mut();
                         state.buffer.insert(0, &text);
                         state.buffer.set_modified(true);
                         recovered_count += 1;
-                        tracing::info!("Recovered unsaved buffer");
+                        tracing::info!("Recovered unsaved buffer: {}", name);
                     }
                 }
                 Ok(RecoveryResult::RecoveredChunks {
@@ -151,29 +207,39 @@ impl Editor {
         if self.time_source.elapsed_since(self.last_auto_save) < interval {
             return Ok(0);
         }
+        if !self.owns_session() {
+            self.last_auto_save = self.time_source.now();
+            return Ok(0);
+        }
 
         // Collect buffer info first to avoid borrow issues
         // Only include buffers that have pending recovery changes AND need auto-save
-        let buffer_info: Vec<_> = self
+        let pending: Vec<_> = self
             .buffers
             .iter()
-            .filter_map(|(buffer_id, state)| {
-                let recovery_pending = state.buffer.is_recovery_pending();
-                if recovery_pending {
-                    let path = state.buffer.file_path().map(|p| p.to_path_buf());
-                    let recovery_id = self.recovery_service.get_buffer_id(path.as_deref());
-                    // Only save if enough time has passed since last recovery save
-                    if self
-                        .recovery_service
-                        .needs_auto_save(&recovery_id, recovery_pending)
-                    {
-                        Some((*buffer_id, recovery_id, path))
-                    } else {
-                        None
-                    }
-                } else {
-                    None
-                }
+            .filter(|(buffer_id, state)| {
+                state.buffer.is_recovery_pending()
+                    && !self
+                        .buffer_metadata
+                        .get(buffer_id)
+                        .is_some_and(|metadata| metadata.is_virtual())
+            })
+            .map(|(buffer_id, state)| {
+                (
+                    *buffer_id,
+                    state.buffer.file_path().map(|p| p.to_path_buf()),
+                )
+            })
+            .collect();
+        let now = self.time_source.now();
+        let buffer_info: Vec<_> = pending
+            .into_iter()
+            .filter_map(|(buffer_id, path)| {
+                let (recovery_id, buffer_name) = self.recovery_identity(buffer_id, path.as_deref());
+                // Only save if enough time has passed since last recovery save
+                self.recovery_service
+                    .needs_auto_save(&recovery_id, true, now)
+                    .then_some((buffer_id, recovery_id, path, buffer_name))
             })
             .collect();
 
@@ -186,91 +252,138 @@ impl Editor {
 
         let mut saved_count = 0;
 
-        for (buffer_id, recovery_id, path) in buffer_info {
-            if let Some(state) = self.buffers.get_mut(&buffer_id) {
-                let line_count = state.buffer.line_count();
-
-                // For large files, use chunked recovery to avoid reading entire file
-                if state.buffer.is_large_file() {
-                    let chunks = state.buffer.get_recovery_chunks();
-
-                    // If no modifications, skip saving (original file is recovery)
-                    if chunks.is_empty() {
-                        state.buffer.set_recovery_pending(false);
-                        continue;
-                    }
-
-                    // Convert to RecoveryChunk format
-                    let recovery_chunks: Vec<_> = chunks
-                        .into_iter()
-                        .map(|(offset, content)| {
-                            crate::services::recovery::types::RecoveryChunk::new(
-                                offset, 0, // For insertions, original_len is 0
-                                content,
-                            )
-                        })
-                        .collect();
-
-                    let original_size = state.buffer.original_file_size().unwrap_or(0);
-                    let final_size = state.buffer.total_bytes();
-
-                    tracing::debug!(
-                        "auto_save_dirty_buffers: large file recovery - original_size={}, final_size={}, path={:?}",
-                        original_size,
-                        final_size,
-                        path
-                    );
-
-                    self.recovery_service.save_buffer(
-                        &recovery_id,
-                        recovery_chunks,
-                        path.as_deref(),
-                        None,
-                        line_count,
-                        original_size,
-                        final_size,
-                    )?;
+        for (buffer_id, recovery_id, path, buffer_name) in buffer_info {
+            // Recovery is written on the worker pool, like saves; a buffer
+            // whose previous recovery write is still running waits for the
+            // next round.
+            if self.recovery_writes.contains_key(&buffer_id) {
+                continue;
+            }
+            let Some(state) = self.buffers.get_mut(&buffer_id) else {
+                continue;
+            };
+            let line_count = state.buffer.line_count();
+
+            // For large files, only the modified regions are written, to
+            // avoid reading the entire file
+            let large_file_chunks = if state.buffer.is_large_file() {
+                let chunks = state.buffer.get_recovery_chunks();
+
+                // If no modifications, skip saving (original file is recovery)
+                if chunks.is_empty() {
+                    state.buffer.set_recovery_pending(false);
+                    continue;
+                }
 
-                    tracing::debug!(
-                        "Saved chunked recovery for large file (original: {} bytes, final: {} bytes)",
-                        original_size,
-                        final_size
-                    );
-                } else {
-                    // For small files, save full content as a single chunk
-                    let total_bytes = state.buffer.total_bytes();
-                    let content = match state.buffer.get_text_range_mut(0, total_bytes) {
-                        Ok(bytes) => bytes,
-                        Err(e) => {
-                            tracing::warn!("Failed to get buffer content for recovery save: {}", e);
-                            continue;
+                // Convert to RecoveryChunk format
+                let recovery_chunks: Vec<_> = chunks
+                    .into_iter()
+                    .map(|(offset, content)| {
+                        RecoveryChunk::new(
+                            offset, 0, // For insertions, original_len is 0
+                            content,
+                        )
+                    })
+                    .collect();
+
+                let original_size = state.buffer.original_file_size().unwrap_or(0);
+                let final_size = state.buffer.total_bytes();
+
+                tracing::debug!(
+                    "auto_save_dirty_buffers: large file recovery - original_size={}, final_size={}, path={:?}",
+                    original_size,
+                    final_size,
+                    path
+                );
+                Some((recovery_chunks, original_size, final_size))
+            } else {
+                None
+            };
+
+            // Small files and new buffers save their full content as a
+            // single chunk, read from the snapshot on the worker
+            let snapshot = match state.buffer.content_snapshot() {
+                Ok(snapshot) => snapshot,
+                Err(e) => {
+                    tracing::warn!("Failed to get buffer content for recovery save: {}", e);
+                    continue;
+                }
+            };
+            let storage = self.recovery_service.storage().clone();
+            self.recovery_service
+                .record_save(&recovery_id, self.time_source.now());
+            let wake = self.async_bridge.as_ref().map(|bridge| bridge.sender());
+            let save_path = PathBuf::from(&recovery_id);
+            let mut write = FileSave::start_with(
+                &self.worker_pool,
+                &save_path,
+                snapshot,
+                wake,
+                move |snapshot| {
+                    let (chunks, original_size, final_size) = match large_file_chunks {
+                        Some(large_file_chunks) => large_file_chunks,
+                        None => {
+                            let mut content = Vec::with_capacity(snapshot.len());
+                            snapshot.write_to(&mut content)?;
+                            // original_file_size = 0 for new/small files
+                            (vec![RecoveryChunk::new(0, 0, content)], 0, snapshot.len())
                         }
                     };
-
-                    let chunks = vec![crate::services::recovery::types::RecoveryChunk::new(
-                        0, 0, content,
-                    )];
-                    self.recovery_service.save_buffer(
+                    storage.save_recovery(
                         &recovery_id,
                         chunks,
                         path.as_deref(),
-                        None,
+                        buffer_name.as_deref(),
                         line_count,
-                        0,           // original_file_size = 0 for new/small files
-                        total_bytes, // final_size
+                        original_size,
+                        final_size,
                     )?;
+                    Ok(final_size as u64)
+                },
+            );
+            match write.wait(self.quick_save_timeout) {
+                Some(result) => {
+                    result?;
                 }
+                None => {
+                    self.recovery_writes.insert(buffer_id, write);
+                }
+            }
 
-                // Clear recovery_pending flag after successful save
+            // Clear recovery_pending flag after successful save
+            if let Some(state) = self.buffers.get_mut(&buffer_id) {
                 state.buffer.set_recovery_pending(false);
-                saved_count += 1;
             }
+            saved_count += 1;
         }
 
         self.last_auto_save = self.time_source.now();
         Ok(saved_count)
     }
 
+    /// Collect recovery writes that finished in the background
+    ///
+    /// A buffer whose write failed is written again on the next round.
+    pub(super) fn poll_recovery_writes(&mut self) {
+        if self.recovery_writes.is_empty() {
+            return;
+        }
+        let finished: Vec<_> = self
+            .recovery_writes
+            .iter_mut()
+            .filter_map(|(buffer_id, write)| write.poll().map(|result| (*buffer_id, result)))
+            .collect();
+        for (buffer_id, result) in finished {
+            self.recovery_writes.remove(&buffer_id);
+            if let Err(e) = result {
+                tracing::warn!("Failed to write recovery for {:?}: {}", buffer_id, e);
+                if let Some(state) = self.buffers.get_mut(&buffer_id) {
+                    state.buffer.set_recovery_pending(true);
+                }
+            }
+        }
+    }
+
     /// Check if the active buffer is marked dirty for recovery auto-save
     /// Used for testing to verify that edits properly trigger recovery tracking
     pub fn is_active_buffer_recovery_dirty(&self) -> bool {
@@ -283,10 +396,23 @@ impl Editor {
 
     /// Delete recovery for a buffer (call after saving or closing)
     pub fn delete_buffer_recovery(&mut self, buffer_id: BufferId) -> io::Result<()> {
+        // A recovery write still running would bring back what is deleted here
+        if let Some(mut write) = self.recovery_writes.remove(&buffer_id) {
+            let _ = write.wait(RECOVERY_WRITE_TIMEOUT);
+        }
+        // An untitled buffer that was just saved under a name leaves its
+        // untitled snapshot behind
+        let untitled_id = self.untitled_recovery_ids.remove(&buffer_id);
+        // Recovery files belong to the instance owning the session
+        let owns_session = self.owns_session();
+        if let Some(untitled_id) = untitled_id.filter(|_| owns_session) {
+            self.recovery_service.delete_buffer_recovery(&untitled_id)?;
+        }
         if let Some(state) = self.buffers.get_mut(&buffer_id) {
-            let path = state.buffer.file_path().map(|p| p.to_path_buf());
-            let recovery_id = self.recovery_service.get_buffer_id(path.as_deref());
-            self.recovery_service.delete_buffer_recovery(&recovery_id)?;
+            if let Some(path) = state.buffer.file_path().filter(|_| owns_session) {
+                let recovery_id = self.recovery_service.get_buffer_id(Some(path));
+                self.recovery_service.delete_buffer_recovery(&recovery_id)?;
+            }
             // Clear recovery_pending since buffer is now saved
             state.buffer.set_recovery_pending(false);
         }
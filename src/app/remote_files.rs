@@ -0,0 +1,427 @@
+//! Files on other machines
+//!
+//! Paths written as URLs, such as `ssh://me@host/etc/hosts`, are opened,
+//! saved and listed through the [`FileProvider`] for their scheme instead of
+//! the local file system (see [`crate::services::remote`]). Remote files load
+//! and save in the background like files on slow mounts, showing their
+//! progress; a failed save leaves the buffer modified and offers to retry or
+//! to keep a local copy. Remote buffers aren't watched for changes and don't
+//! start language servers.
+//!
+//! Opening a remote directory shows its entries in a read-only listing
+//! buffer; Enter opens the file under the cursor, or lists the directory
+//! under it in the same buffer.
+
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use rust_i18n::t;
+use serde_json::json;
+
+use crate::model::buffer::replace_file;
+use crate::model::event::BufferId;
+use crate::primitives::text_property::TextPropertyEntry;
+use crate::services::async_bridge::AsyncMessage;
+use crate::services::file_provider::{remote_parent, remote_scheme, FileProvider};
+use crate::services::fs::{FsEntry, FsEntryType};
+use crate::services::remote::{RemotePath, SshFileProvider, SSH_SCHEME};
+use crate::services::worker_pool::{JobHandle, JobPriority};
+
+use super::Editor;
+
+/// Buffer mode of remote directory listings (bindings live in `ModeRegistry::new`)
+pub(crate) const REMOTE_DIR_MODE: &str = "remote-dir";
+
+/// An open listing of a remote directory
+pub(crate) struct RemoteDirListing {
+    /// URL of the directory
+    path: PathBuf,
+    /// Entries of the last finished listing, or why it failed
+    entries: Option<Result<Vec<FsEntry>, String>>,
+    /// Listing in progress and its id
+    listing: Option<(u64, JobHandle)>,
+}
+
+impl RemoteDirListing {
+    /// Build the listing buffer content
+    fn entries(&self) -> Vec<TextPropertyEntry> {
+        let mut entries = vec![
+            TextPropertyEntry::text(format!(
+                "{}\n",
+                t!("remote.dir_title", path = self.path.display().to_string())
+            ))
+            .with_property("type", json!("header")),
+            TextPropertyEntry::text(format!("{}\n\n", t!("remote.dir_help")))
+                .with_property("type", json!("help")),
+        ];
+
+        if let Some(parent) = remote_parent(&self.path) {
+            entries.push(
+                TextPropertyEntry::text("  ../\n".to_string())
+                    .with_property("type", json!("entry"))
+                    .with_property("path", json!(parent.to_string_lossy()))
+                    .with_property("dir", json!(true)),
+            );
+        }
+
+        match &self.entries {
+            None => entries.push(TextPropertyEntry::text(format!(
+                "  {}\n",
+                t!("remote.dir_listing")
+            ))),
+            Some(Err(error)) => entries.push(
+                TextPropertyEntry::text(format!("  {}\n", t!("remote.dir_failed", error = error)))
+                    .with_property("type", json!("error")),
+            ),
+            Some(Ok(listed)) => {
+                let mut listed: Vec<&FsEntry> = listed.iter().collect();
+                listed.sort_by(|a, b| b.is_dir().cmp(&a.is_dir()).then(a.name.cmp(&b.name)));
+                for entry in listed {
+                    let suffix = if entry.is_dir() { "/" } else { "" };
+                    entries.push(
+                        TextPropertyEntry::text(format!("  {}{}\n", entry.name, suffix))
+                            .with_property("type", json!("entry"))
+                            .with_property("path", json!(entry.path.to_string_lossy()))
+                            .with_property("dir", json!(entry.is_dir())),
+                    );
+                }
+            }
+        }
+
+        entries
+    }
+}
+
+impl Editor {
+    /// Replace the provider of paths with `scheme` (used by tests to simulate remote hosts)
+    pub fn set_file_provider(&mut self, scheme: &str, provider: Arc<dyn FileProvider>) {
+        self.file_providers.insert(scheme.to_string(), provider);
+    }
+
+    /// Provider of a remote path, or None for a local one
+    pub(super) fn remote_file_provider(
+        &self,
+        path: &Path,
+    ) -> io::Result<Option<Arc<dyn FileProvider>>> {
+        let Some(scheme) = remote_scheme(path) else {
+            return Ok(None);
+        };
+        if let Some(provider) = self.file_providers.get(scheme) {
+            return Ok(Some(Arc::clone(provider)));
+        }
+        if scheme == SSH_SCHEME {
+            let files = &self.config.files;
+            let control_dir = files
+                .ssh_control_master
+                .then(|| self.dir_context.ssh_control_dir())
+                .flatten();
+            return Ok(Some(Arc::new(SshFileProvider::new(
+                files.ssh_args.clone(),
+                control_dir,
+            ))));
+        }
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            t!("remote.unsupported_scheme", scheme = scheme).to_string(),
+        ))
+    }
+
+    /// Open a remote file or directory without switching focus to it
+    ///
+    /// Directories get a listing buffer; files that don't exist yet get an
+    /// empty buffer that creates them when saved.
+    pub(super) fn open_remote_no_focus(&mut self, path: &Path) -> io::Result<BufferId> {
+        let Some(provider) = self.remote_file_provider(path)? else {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("not a remote path: {}", path.display()),
+            ));
+        };
+        let path = if remote_scheme(path) == Some(SSH_SCHEME) {
+            RemotePath::from_path(path)?.to_path_buf()
+        } else {
+            path.to_path_buf()
+        };
+
+        if let Some(buffer_id) = self.remote_dir_buffer(&path) {
+            self.start_remote_dir_listing(buffer_id);
+            return Ok(buffer_id);
+        }
+        let already_open = self
+            .buffers
+            .iter()
+            .find(|(_, state)| state.buffer.file_path() == Some(path.as_path()))
+            .map(|(id, _)| *id);
+        if let Some(buffer_id) = already_open {
+            return Ok(buffer_id);
+        }
+
+        match provider.entry_type(&path)? {
+            Some(FsEntryType::Directory) => Ok(self.open_remote_dir(&path)),
+            entry_type => self.open_file_at(&path, entry_type.is_some()),
+        }
+    }
+
+    /// The listing buffer showing the remote directory `path`, if any
+    fn remote_dir_buffer(&self, path: &Path) -> Option<BufferId> {
+        self.remote_dirs
+            .iter()
+            .find(|(_, listing)| listing.path == path)
+            .map(|(id, _)| *id)
+    }
+
+    /// Open a listing buffer for the remote directory `path` and list it
+    fn open_remote_dir(&mut self, path: &Path) -> BufferId {
+        let buffer_id = self.create_virtual_buffer(
+            path.display().to_string(),
+            REMOTE_DIR_MODE.to_string(),
+            true,
+        );
+        if let Some(state) = self.buffers.get_mut(&buffer_id) {
+            state.editing_disabled = true;
+            state.margins.set_line_numbers(false);
+        }
+        self.remote_dirs.insert(
+            buffer_id,
+            RemoteDirListing {
+                path: path.to_path_buf(),
+                entries: None,
+                listing: None,
+            },
+        );
+        self.start_remote_dir_listing(buffer_id);
+        buffer_id
+    }
+
+    /// List a listing buffer's directory, replacing any listing in progress
+    fn start_remote_dir_listing(&mut self, buffer_id: BufferId) {
+        static NEXT_LISTING_ID: AtomicU64 = AtomicU64::new(1);
+
+        let Some(path) = self
+            .remote_dirs
+            .get(&buffer_id)
+            .map(|listing| listing.path.clone())
+        else {
+            return;
+        };
+        let provider = match self.remote_file_provider(&path) {
+            Ok(Some(provider)) => provider,
+            Ok(None) => return,
+            Err(e) => {
+                if let Some(listing) = self.remote_dirs.get_mut(&buffer_id) {
+                    listing.entries = Some(Err(e.to_string()));
+                }
+                self.render_remote_dir(buffer_id);
+                return;
+            }
+        };
+
+        let listing_id = NEXT_LISTING_ID.fetch_add(1, Ordering::Relaxed);
+        let job_path = path.clone();
+        let job = self
+            .worker_pool
+            .submit("remote-dir", JobPriority::UserBlocking, move |token| {
+                let result = provider.read_dir(&job_path).map_err(|e| e.to_string());
+                if token.is_cancelled() {
+                    return None;
+                }
+                Some(AsyncMessage::RemoteDirListed { listing_id, result })
+            });
+
+        let Some(listing) = self.remote_dirs.get_mut(&buffer_id) else {
+            job.cancel();
+            return;
+        };
+        if let Some((_, previous)) = listing.listing.replace((listing_id, job)) {
+            previous.cancel();
+        }
+        self.render_remote_dir(buffer_id);
+        self.set_status_message(
+            t!("remote.dir_listing_name", path = path.display().to_string()).to_string(),
+        );
+    }
+
+    /// Show the result of a finished listing
+    pub(super) fn handle_remote_dir_listed(
+        &mut self,
+        listing_id: u64,
+        result: Result<Vec<FsEntry>, String>,
+    ) {
+        let Some((&buffer_id, listing)) = self
+            .remote_dirs
+            .iter_mut()
+            .find(|(_, listing)| listing.listing.as_ref().map(|(id, _)| *id) == Some(listing_id))
+        else {
+            return;
+        };
+        listing.listing = None;
+        let message = match &result {
+            Ok(entries) => t!("remote.dir_listed", count = entries.len()).to_string(),
+            Err(error) => t!("remote.dir_failed", error = error).to_string(),
+        };
+        listing.entries = Some(result);
+        self.render_remote_dir(buffer_id);
+        self.set_status_message(message);
+
+        // Start on the first entry rather than the header
+        if let Some(state) = self.buffers.get_mut(&buffer_id) {
+            let first_entry = state
+                .text_properties
+                .all()
+                .iter()
+                .find(|p| p.get("type") == Some(&json!("entry")))
+                .map(|p| p.start);
+            if let Some(position) = first_entry {
+                state.cursors.primary_mut().position = position;
+                state.cursors.primary_mut().anchor = None;
+            }
+        }
+    }
+
+    /// Re-render a listing buffer from its listing
+    fn render_remote_dir(&mut self, buffer_id: BufferId) {
+        let Some(listing) = self.remote_dirs.get(&buffer_id) else {
+            return;
+        };
+        let entries = listing.entries();
+        if let Err(e) = self.set_virtual_buffer_content(buffer_id, entries) {
+            tracing::error!("Failed to render remote directory listing: {}", e);
+        }
+    }
+
+    /// Open the entry under the cursor in a remote directory listing
+    pub(super) fn remote_dir_open(&mut self) {
+        let buffer_id = self.active_buffer();
+        if !self.remote_dirs.contains_key(&buffer_id) {
+            return;
+        }
+        let target = self.get_text_properties_at_cursor().and_then(|properties| {
+            properties.into_iter().find_map(|p| {
+                let path = PathBuf::from(p.get("path")?.as_str()?);
+                let is_dir = p.get("dir").and_then(|v| v.as_bool()).unwrap_or(false);
+                Some((path, is_dir))
+            })
+        });
+        let Some((path, is_dir)) = target else {
+            return;
+        };
+
+        if !is_dir {
+            if let Err(e) = self.open_file(&path) {
+                self.set_status_message(
+                    t!("file.error_opening", error = e.to_string()).to_string(),
+                );
+            }
+            return;
+        }
+        // Directories are listed in the same buffer
+        if let Some(listing) = self.remote_dirs.get_mut(&buffer_id) {
+            listing.path = path.clone();
+            listing.entries = None;
+        }
+        if let Some(metadata) = self.buffer_metadata.get_mut(&buffer_id) {
+            metadata.display_name = path.display().to_string();
+        }
+        self.start_remote_dir_listing(buffer_id);
+    }
+
+    /// Forget a remote directory listing when its buffer is closed
+    pub(super) fn on_remote_dir_buffer_closed(&mut self, buffer_id: BufferId) {
+        if let Some((_, job)) = self
+            .remote_dirs
+            .remove(&buffer_id)
+            .and_then(|listing| listing.listing)
+        {
+            job.cancel();
+        }
+    }
+
+    /// Write a remote buffer's contents to a file in the state directory
+    ///
+    /// Offered when saving the remote file fails. The buffer stays modified
+    /// and keeps its remote file, so saving it there can be tried again.
+    pub(super) fn save_remote_local_copy(&mut self, buffer_id: BufferId) {
+        let Some(state) = self.buffers.get(&buffer_id) else {
+            return;
+        };
+        let Some(path) = state.buffer.file_path().map(Path::to_path_buf) else {
+            return;
+        };
+        let local = self
+            .dir_context
+            .remote_copies_dir()
+            .join(local_copy_name(&path));
+        let result = state.buffer.save_snapshot().and_then(|snapshot| {
+            if let Some(dir) = local.parent() {
+                std::fs::create_dir_all(dir)?;
+            }
+            replace_file(&local, |out| snapshot.write_to(out))
+        });
+        match result {
+            Ok(_) => {
+                tracing::info!(
+                    "Saved a local copy of {} to {}",
+                    path.display(),
+                    local.display()
+                );
+                self.set_status_message(
+                    t!(
+                        "remote.local_copy_saved",
+                        path = local.display().to_string()
+                    )
+                    .to_string(),
+                );
+            }
+            Err(e) => {
+                tracing::error!("Failed to save a local copy of {}: {}", path.display(), e);
+                self.set_status_message(
+                    t!("remote.local_copy_failed", error = e.to_string()).to_string(),
+                );
+            }
+        }
+    }
+}
+
+/// Relative path of the local copy of a remote file: the URL without its
+/// `://`, such as `ssh/me@host/etc/hosts` for `ssh://me@host/etc/hosts`
+///
+/// `..` is resolved, but never past the host, so the copy stays in the
+/// host's directory.
+fn local_copy_name(path: &Path) -> PathBuf {
+    let text = path.to_string_lossy();
+    let (scheme, rest) = text.split_once("://").unwrap_or(("", &text));
+    let mut parts: Vec<&str> = Vec::new();
+    for part in rest.split('/') {
+        match part {
+            "" | "." => {}
+            ".." => {
+                if parts.len() > 1 {
+                    parts.pop();
+                }
+            }
+            _ => parts.push(part),
+        }
+    }
+    let mut name = PathBuf::from(scheme);
+    name.extend(parts);
+    name
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_local_copy_name() {
+        assert_eq!(
+            local_copy_name(Path::new("ssh://me@host/etc/../hosts")),
+            PathBuf::from("ssh/me@host/hosts")
+        );
+        assert_eq!(
+            local_copy_name(Path::new("ssh://host/~/notes.txt")),
+            PathBuf::from("ssh/host/~/notes.txt")
+        );
+    }
+}
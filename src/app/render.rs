@@ -1,4 +1,6 @@
 use super::*;
+use crate::view::glyphs::glyphs;
+use crate::view::split::{search_namespace, SearchState};
 use rust_i18n::t;
 
 impl Editor {
@@ -7,6 +9,13 @@ impl Editor {
         let _span = tracing::trace_span!("render").entered();
         let size = frame.area();
 
+        if crate::view::ui::too_small::is_too_small(size) {
+            // Nothing that could be clicked is on screen
+            self.cached_layout = Default::default();
+            crate::view::ui::too_small::render(frame, size, &self.theme);
+            return;
+        }
+
         // For scroll sync groups, we need to update the active split's viewport position BEFORE
         // calling sync_scroll_groups, so that the sync reads the correct position.
         // Otherwise, cursor movements like 'G' (go to end) won't sync properly because
@@ -26,6 +35,7 @@ impl Editor {
         for (split_id, view_state) in &self.split_view_states {
             if let Some(buffer_id) = self.split_manager.get_buffer_id(*split_id) {
                 if let Some(state) = self.buffers.get_mut(&buffer_id) {
+                    self.gutter_columns.sync(&mut state.margins);
                     let top_byte = view_state.viewport.top_byte;
                     let height = view_state.viewport.height;
                     if let Err(e) = state.prepare_for_render(top_byte, height) {
@@ -36,11 +46,24 @@ impl Editor {
             }
         }
 
-        // Refresh search highlights for the current viewport if we have an active search
+        // Refresh search highlights for the current viewport of every split with an active search
         // This ensures highlights update when scrolling to show matches in the new viewport
-        if let Some(ref search_state) = self.search_state {
-            let query = search_state.query.clone();
-            self.update_search_highlights(&query);
+        let searches: Vec<_> = self
+            .split_view_states
+            .iter()
+            .filter_map(|(split_id, view_state)| {
+                let search = view_state.search.as_ref()?;
+                Some((
+                    *split_id,
+                    search.query.clone(),
+                    search.case_sensitive,
+                    search.whole_word,
+                    search.use_regex,
+                ))
+            })
+            .collect();
+        for (split_id, query, case_sensitive, whole_word, use_regex) in searches {
+            self.highlight_search_in_split(split_id, &query, case_sensitive, whole_word, use_regex);
         }
 
         // Determine if we need to show search options bar
@@ -63,21 +86,20 @@ impl Editor {
         let has_file_browser = self.prompt.as_ref().map_or(false, |p| {
             matches!(
                 p.prompt_type,
-                PromptType::OpenFile | PromptType::SwitchProject
+                PromptType::OpenFile | PromptType::InsertFile | PromptType::SwitchProject
             )
         }) && self.file_open_state.is_some();
 
+        // Status bar is hidden when suggestions popup is shown, and in distraction-free mode
+        let show_status_bar =
+            !has_suggestions && !has_file_browser && self.distraction_free.is_none();
+
         // Build main vertical layout: [menu_bar, main_content, status_bar, search_options, prompt_line]
-        // Status bar is hidden when suggestions popup is shown
         // Search options bar is shown when in search prompt
         let constraints = vec![
             Constraint::Length(if self.menu_bar_visible { 1 } else { 0 }), // Menu bar
             Constraint::Min(0),                                            // Main content area
-            Constraint::Length(if has_suggestions || has_file_browser {
-                0
-            } else {
-                1
-            }), // Status bar (hidden with popups)
+            Constraint::Length(if show_status_bar { 1 } else { 0 }),       // Status bar
             Constraint::Length(if show_search_options { 1 } else { 0 }),   // Search options bar
             Constraint::Length(1), // Prompt line (always reserved)
         ];
@@ -162,11 +184,15 @@ impl Editor {
 
         // Note: Tabs are now rendered within each split by SplitRenderer
 
+        self.update_inline_blame(editor_content_area);
+
         // Trigger lines_changed hooks for newly visible lines in all visible buffers
-        // This allows plugins to add overlays before rendering
+        // This allows plugins (and the built-in TODO highlighter) to add overlays before rendering
         // Only lines that haven't been seen before are sent (batched for efficiency)
         // Use non-blocking hooks to avoid deadlock when actions are awaiting
-        if self.plugin_manager.is_active() {
+        let plugins_active = self.plugin_manager.is_active();
+        let highlight_todos = self.config.todos.highlight;
+        if plugins_active || highlight_todos {
             let hooks_start = std::time::Instant::now();
             // Get visible buffers and their areas
             let visible_buffers = self.split_manager.get_visible_buffers(editor_content_area);
@@ -181,41 +207,43 @@ impl Editor {
                     .unwrap_or(0);
 
                 if let Some(state) = self.buffers.get_mut(&buffer_id) {
-                    // Fire render_start hook once per buffer
-                    self.plugin_manager.run_hook(
-                        "render_start",
-                        crate::services::plugins::hooks::HookArgs::RenderStart { buffer_id },
-                    );
+                    if plugins_active {
+                        // Fire render_start hook once per buffer
+                        self.plugin_manager.run_hook(
+                            "render_start",
+                            crate::services::plugins::hooks::HookArgs::RenderStart { buffer_id },
+                        );
 
-                    // Fire view_transform_request hook with base tokens
-                    // This allows plugins to transform the view (e.g., soft breaks for markdown)
-                    let visible_count = split_area.height as usize;
-                    let is_binary = state.buffer.is_binary();
-                    let line_ending = state.buffer.line_ending();
-                    let base_tokens =
-                        crate::view::ui::split_rendering::SplitRenderer::build_base_tokens_for_hook(
-                            &mut state.buffer,
-                            viewport_top_byte,
-                            self.config.editor.estimated_line_length,
-                            visible_count,
-                            is_binary,
-                            line_ending,
+                        // Fire view_transform_request hook with base tokens
+                        // This allows plugins to transform the view (e.g., soft breaks for markdown)
+                        let visible_count = split_area.height as usize;
+                        let is_binary = state.buffer.is_binary();
+                        let line_ending = state.buffer.line_ending();
+                        let base_tokens =
+                            crate::view::ui::split_rendering::SplitRenderer::build_base_tokens_for_hook(
+                                &mut state.buffer,
+                                viewport_top_byte,
+                                self.config.editor.estimated_line_length,
+                                visible_count,
+                                is_binary,
+                                line_ending,
+                            );
+                        let viewport_start = viewport_top_byte;
+                        let viewport_end = base_tokens
+                            .last()
+                            .and_then(|t| t.source_offset)
+                            .unwrap_or(viewport_start);
+                        self.plugin_manager.run_hook(
+                            "view_transform_request",
+                            crate::services::plugins::hooks::HookArgs::ViewTransformRequest {
+                                buffer_id,
+                                split_id,
+                                viewport_start,
+                                viewport_end,
+                                tokens: base_tokens,
+                            },
                         );
-                    let viewport_start = viewport_top_byte;
-                    let viewport_end = base_tokens
-                        .last()
-                        .and_then(|t| t.source_offset)
-                        .unwrap_or(viewport_start);
-                    self.plugin_manager.run_hook(
-                        "view_transform_request",
-                        crate::services::plugins::hooks::HookArgs::ViewTransformRequest {
-                            buffer_id,
-                            split_id,
-                            viewport_start,
-                            viewport_end,
-                            tokens: base_tokens,
-                        },
-                    );
+                    }
 
                     // Use the split area height as visible line count
                     let visible_count = split_area.height as usize;
@@ -233,11 +261,13 @@ impl Editor {
                     let mut iter = state
                         .buffer
                         .line_iterator(top_byte, self.config.editor.estimated_line_length);
+                    let mut visible_end = top_byte;
 
                     for _ in 0..visible_count {
                         if let Some((line_start, line_content)) = iter.next() {
                             let byte_end = line_start + line_content.len();
                             let byte_range = (line_start, byte_end);
+                            visible_end = byte_end;
 
                             // Only add if this byte range hasn't been seen before
                             if !seen_byte_ranges.contains(&byte_range) {
@@ -258,13 +288,22 @@ impl Editor {
                     // Send batched hook if there are new lines
                     if !new_lines.is_empty() {
                         total_new_lines += new_lines.len();
-                        self.plugin_manager.run_hook(
-                            "lines_changed",
-                            crate::services::plugins::hooks::HookArgs::LinesChanged {
+                        if highlight_todos {
+                            self.highlight_todo_keywords(
                                 buffer_id,
-                                lines: new_lines,
-                            },
-                        );
+                                &new_lines,
+                                top_byte..visible_end,
+                            );
+                        }
+                        if plugins_active {
+                            self.plugin_manager.run_hook(
+                                "lines_changed",
+                                crate::services::plugins::hooks::HookArgs::LinesChanged {
+                                    buffer_id,
+                                    lines: new_lines,
+                                },
+                            );
+                        }
                     }
                 }
             }
@@ -283,6 +322,7 @@ impl Editor {
                     tracing::error!("Error handling plugin command: {}", e);
                 }
             }
+            self.plugin_manager.commands_applied();
         }
 
         // Render editor content (same for both layouts)
@@ -323,31 +363,52 @@ impl Editor {
         };
 
         let is_maximized = self.split_manager.is_maximized();
-
-        let (split_areas, tab_areas, close_split_areas, maximize_split_areas, view_line_mappings) =
-            SplitRenderer::render_content(
-                frame,
-                editor_content_area,
-                &self.split_manager,
-                &mut self.buffers,
-                &self.buffer_metadata,
-                &mut self.event_logs,
-                &self.theme,
-                self.ansi_background.as_ref(),
-                self.background_fade,
-                lsp_waiting,
-                self.config.editor.large_file_threshold_bytes,
-                self.config.editor.line_wrap,
-                self.config.editor.estimated_line_length,
-                self.config.editor.highlight_context_bytes,
-                Some(&mut self.split_view_states),
-                hide_cursor,
-                hovered_tab,
-                hovered_close_split,
-                hovered_maximize_split,
-                is_maximized,
-                self.config.editor.relative_line_numbers,
-            );
+        self.contrast_cache
+            .set_minimum(self.config.ui.minimum_contrast);
+
+        let (
+            split_areas,
+            tab_areas,
+            close_split_areas,
+            maximize_split_areas,
+            view_line_mappings,
+            winbar_areas,
+        ) = SplitRenderer::render_content(
+            frame,
+            editor_content_area,
+            &self.split_manager,
+            &mut self.buffers,
+            &self.buffer_metadata,
+            &mut self.event_logs,
+            &self.theme,
+            self.ansi_background.as_ref(),
+            self.background_fade,
+            lsp_waiting,
+            self.config.editor.large_file_threshold_bytes,
+            self.config.editor.line_wrap,
+            self.config.editor.estimated_line_length,
+            self.config.editor.highlight_context_bytes,
+            Some(&mut self.split_view_states),
+            hide_cursor,
+            hovered_tab,
+            hovered_close_split,
+            hovered_maximize_split,
+            is_maximized,
+            self.distraction_free.is_some(),
+            self.config.editor.relative_line_numbers,
+            self.config.ui.render_whitespace,
+            &self.config.ui.rulers,
+            self.config.ui.highlight_overlength,
+            &mut self.contrast_cache,
+            (self.config.editor.data_path_breadcrumb == crate::config::DataPathBreadcrumb::Winbar)
+                .then_some(self.config.editor.data_path_style),
+            if self.distraction_free.is_some() {
+                crate::config::WinbarVisibility::Never
+            } else {
+                self.config.ui.winbar
+            },
+            self.time_source.now(),
+        );
 
         // Detect viewport changes and fire hooks
         // Compare against previous frame's viewport state (stored in self.previous_viewports)
@@ -418,11 +479,14 @@ impl Editor {
         self.cached_layout.close_split_areas = close_split_areas;
         self.cached_layout.maximize_split_areas = maximize_split_areas;
         self.cached_layout.view_line_mappings = view_line_mappings;
+        self.cached_layout.winbar_areas = winbar_areas;
         self.cached_layout.separator_areas = self
             .split_manager
             .get_separators_with_ids(editor_content_area);
         self.cached_layout.editor_content_area = Some(editor_content_area);
 
+        self.render_distraction_free_dimming(frame);
+
         // Render hover highlights for separators and scrollbars
         self.render_hover_highlights(frame);
 
@@ -430,10 +494,10 @@ impl Editor {
         self.cached_layout.suggestions_area = None;
         self.file_browser_layout = None;
         if let Some(prompt) = &self.prompt {
-            // For OpenFile/SwitchProject prompt, render the file browser popup
+            // For OpenFile/InsertFile/SwitchProject prompt, render the file browser popup
             if matches!(
                 prompt.prompt_type,
-                PromptType::OpenFile | PromptType::SwitchProject
+                PromptType::OpenFile | PromptType::InsertFile | PromptType::SwitchProject
             ) {
                 if let Some(file_open_state) = &self.file_open_state {
                     // Calculate popup area: position above prompt line, covering status bar
@@ -458,7 +522,8 @@ impl Editor {
                 // For other prompts, render suggestions as before
                 // Calculate overlay area: position above prompt line (which is below status bar)
                 let suggestion_count = prompt.suggestions.len().min(10);
-                let height = suggestion_count as u16 + 2; // +2 for borders
+                // +2 for borders, and no taller than the rows above the prompt
+                let height = (suggestion_count as u16 + 2).min(main_chunks[prompt_line_idx].y);
 
                 // Position suggestions above the prompt line
                 // The prompt line is at main_chunks[3], so suggestions go above it
@@ -486,7 +551,13 @@ impl Editor {
         let display_name = self
             .buffer_metadata
             .get(&self.active_buffer())
-            .map(|m| m.display_name.clone())
+            .map(|m| {
+                if m.follow_tail {
+                    format!("{} {}", m.display_name, t!("status.follow_tail_indicator"))
+                } else {
+                    m.display_name.clone()
+                }
+            })
             .unwrap_or_else(|| "[No Name]".to_string());
         let status_message = self.status_message.clone();
         let plugin_status_message = self.plugin_status_message.clone();
@@ -500,7 +571,7 @@ impl Editor {
         let update_available = self.latest_version().map(|v| v.to_string());
 
         // Render status bar (hidden when suggestions or file browser popup is shown)
-        if !has_suggestions && !has_file_browser {
+        if show_status_bar {
             // Get warning level for colored indicator (respects config setting)
             let (warning_level, general_warning_count) =
                 if self.config.warnings.show_status_indicator {
@@ -512,6 +583,32 @@ impl Editor {
                     (WarningLevel::None, 0)
                 };
 
+            let blocked_command_count = self.blocked_command_count();
+
+            let data_path = if self.config.editor.data_path_breadcrumb
+                == crate::config::DataPathBreadcrumb::StatusBar
+            {
+                self.data_path_at_cursor()
+            } else {
+                None
+            };
+
+            let input_locked = self.is_split_locked(self.split_manager.active_split());
+            let read_only = self.is_active_file_read_only();
+            let protected = self.is_active_file_protected();
+            let search_match = self.active_search().and_then(|search| {
+                search
+                    .current_match_index
+                    .map(|index| (index + 1, search.matches.len()))
+            });
+            let progress = self.progress_status();
+            let diagnostics = self
+                .buffer_metadata
+                .get(&self.active_buffer())
+                .and_then(|metadata| metadata.file_path())
+                .map(|path| self.stored_diagnostics.counts_for_path(path))
+                .unwrap_or_default();
+
             // Compute status bar hover state for styling
             use crate::view::ui::status_bar::StatusBarHover;
             let status_bar_hover = match &self.mouse_state.hover_target {
@@ -537,7 +634,15 @@ impl Editor {
                 update_available.as_deref(), // Pass update availability
                 warning_level,               // Pass warning level for colored indicator
                 general_warning_count,       // Pass general warning count for badge
+                blocked_command_count,       // Pass blocked project command count for badge
                 status_bar_hover,            // Pass hover state for indicator styling
+                data_path.as_deref(),        // Pass the key path at the cursor
+                input_locked,                // Pass the focused split's input lock
+                read_only,                   // Pass whether the file can't be written
+                protected,                   // Pass whether the file is protected and locked
+                search_match,                // Pass the focused split's current search match
+                progress.as_deref(),         // Pass the single long operation in progress
+                diagnostics,                 // Pass the diagnostic counts of the buffer's file
             );
 
             // Store status bar layout for click detection
@@ -546,8 +651,18 @@ impl Editor {
                 Some((status_bar_area.y, status_bar_area.x, status_bar_area.width));
             self.cached_layout.status_bar_lsp_area = status_bar_layout.lsp_indicator;
             self.cached_layout.status_bar_warning_area = status_bar_layout.warning_badge;
+            self.cached_layout.status_bar_blocked_area = status_bar_layout.blocked_commands_badge;
+            self.cached_layout.status_bar_progress_area = status_bar_layout.progress_indicator;
             self.cached_layout.status_bar_line_ending_area =
                 status_bar_layout.line_ending_indicator;
+        } else {
+            // Keep clicks from reaching the hidden status bar
+            self.cached_layout.status_bar_area = None;
+            self.cached_layout.status_bar_lsp_area = None;
+            self.cached_layout.status_bar_warning_area = None;
+            self.cached_layout.status_bar_blocked_area = None;
+            self.cached_layout.status_bar_progress_area = None;
+            self.cached_layout.status_bar_line_ending_area = None;
         }
 
         // Render search options bar when in search prompt
@@ -567,6 +682,10 @@ impl Editor {
                 }
             });
 
+            // Case matching of the query being typed, as the search will use it
+            let case_sensitive =
+                self.search_is_case_sensitive(self.search_prompt_query().unwrap_or_default());
+
             // Determine hover state for search options
             use crate::view::ui::status_bar::SearchOptionsHover;
             let search_options_hover = match &self.mouse_state.hover_target {
@@ -580,7 +699,8 @@ impl Editor {
             let search_options_layout = StatusBarRenderer::render_search_options(
                 frame,
                 main_chunks[search_options_idx],
-                self.search_case_sensitive,
+                case_sensitive,
+                self.search_smart_case(),
                 self.search_whole_word,
                 self.search_use_regex,
                 confirm_each,
@@ -599,6 +719,7 @@ impl Editor {
             if matches!(
                 prompt.prompt_type,
                 crate::view::prompt::PromptType::OpenFile
+                    | crate::view::prompt::PromptType::InsertFile
                     | crate::view::prompt::PromptType::SwitchProject
             ) {
                 if let Some(file_open_state) = &self.file_open_state {
@@ -706,6 +827,10 @@ impl Editor {
         // Store popup areas for mouse hit testing
         self.cached_layout.popup_areas = popup_info.clone();
 
+        // A plugin's anchored popup goes under the built-in popups
+        let anchored_bounds = self.cached_layout.editor_content_area.unwrap_or(size);
+        self.render_anchored_popup(frame, anchored_bounds);
+
         // Now render popups
         let state = self.active_state_mut();
         if state.popups.is_visible() {
@@ -773,6 +898,12 @@ impl Editor {
             }
         }
 
+        // Render notifications above the status bar
+        self.render_notifications(frame);
+
+        // Render input latency HUD (diagnostics overlay)
+        self.render_input_latency_hud(frame);
+
         // Render software mouse cursor when GPM is active
         // GPM can't draw its cursor on the alternate screen buffer used by TUI apps,
         // so we draw our own cursor at the tracked mouse position.
@@ -810,6 +941,9 @@ impl Editor {
             }
         }
 
+        // Keep cells the terminal draws wide ambiguous characters over undrawn
+        crate::view::ambiguous_width::skip_covered_cells(frame.buffer_mut());
+
         // Convert all colors for terminal capability (256/16 color fallback)
         crate::view::color_support::convert_buffer_colors(
             frame.buffer_mut(),
@@ -842,7 +976,7 @@ impl Editor {
                         let hover_style = Style::default().fg(self.theme.split_separator_hover_fg);
                         match dir {
                             SplitDirection::Horizontal => {
-                                let line_text = "─".repeat(*length as usize);
+                                let line_text = glyphs().border_horizontal.repeat(*length as usize);
                                 let paragraph =
                                     Paragraph::new(Span::styled(line_text, hover_style));
                                 frame.render_widget(
@@ -852,7 +986,10 @@ impl Editor {
                             }
                             SplitDirection::Vertical => {
                                 for offset in 0..*length {
-                                    let paragraph = Paragraph::new(Span::styled("│", hover_style));
+                                    let paragraph = Paragraph::new(Span::styled(
+                                        glyphs().border_vertical,
+                                        hover_style,
+                                    ));
                                     frame.render_widget(
                                         paragraph,
                                         ratatui::layout::Rect::new(*x, y + offset, 1, 1),
@@ -922,7 +1059,8 @@ impl Editor {
                     let hover_style = Style::default().fg(self.theme.split_separator_hover_fg);
                     let border_x = explorer_area.x + explorer_area.width;
                     for row_offset in 0..explorer_area.height {
-                        let paragraph = Paragraph::new(Span::styled("│", hover_style));
+                        let paragraph =
+                            Paragraph::new(Span::styled(glyphs().border_vertical, hover_style));
                         frame.render_widget(
                             paragraph,
                             ratatui::layout::Rect::new(
@@ -989,13 +1127,18 @@ impl Editor {
             // Pad the label to fill the menu width
             let label = item.label();
             let content_width = (menu_width as usize).saturating_sub(2); // -2 for borders
-            let padded_label = format!(" {:<width$}", label, width = content_width - 1);
+            let padded_label = format!(
+                " {:<width$}",
+                label,
+                width = content_width.saturating_sub(1)
+            );
 
             lines.push(Line::from(vec![Span::styled(padded_label, style)]));
         }
 
         let block = Block::default()
             .borders(Borders::ALL)
+            .border_set(glyphs().border_set())
             .border_style(Style::default().fg(self.theme.menu_border_fg))
             .style(Style::default().bg(self.theme.menu_dropdown_bg));
 
@@ -1047,7 +1190,7 @@ impl Editor {
             TabDropZone::SplitRight(_) => {
                 // Right 50% of the split (matches the actual split size created)
                 let width = (content_rect.width / 2).max(3);
-                let x = content_rect.x + content_rect.width - width;
+                let x = content_rect.x + content_rect.width.saturating_sub(width);
                 ratatui::layout::Rect::new(x, content_rect.y, width, content_rect.height)
             }
             TabDropZone::SplitTop(_) => {
@@ -1063,7 +1206,7 @@ impl Editor {
             TabDropZone::SplitBottom(_) => {
                 // Bottom 50% of the split (matches the actual split size created)
                 let height = (content_rect.height / 2).max(2);
-                let y = content_rect.y + content_rect.height - height;
+                let y = content_rect.y + content_rect.height.saturating_sub(height);
                 ratatui::layout::Rect::new(content_rect.x, y, content_rect.width, height)
             }
         };
@@ -1102,7 +1245,7 @@ impl Editor {
                 // Draw vertical indicator on left edge
                 for y in highlight_area.y..highlight_area.y + highlight_area.height {
                     if let Some(cell) = buf.cell_mut((highlight_area.x, y)) {
-                        cell.set_symbol("▌");
+                        cell.set_symbol(glyphs().drop_left);
                         cell.set_fg(drop_zone_border);
                     }
                 }
@@ -1112,7 +1255,7 @@ impl Editor {
                 let x = highlight_area.x + highlight_area.width - 1;
                 for y in highlight_area.y..highlight_area.y + highlight_area.height {
                     if let Some(cell) = buf.cell_mut((x, y)) {
-                        cell.set_symbol("▐");
+                        cell.set_symbol(glyphs().drop_right);
                         cell.set_fg(drop_zone_border);
                     }
                 }
@@ -1121,7 +1264,7 @@ impl Editor {
                 // Draw horizontal indicator on top edge
                 for x in highlight_area.x..highlight_area.x + highlight_area.width {
                     if let Some(cell) = buf.cell_mut((x, highlight_area.y)) {
-                        cell.set_symbol("▀");
+                        cell.set_symbol(glyphs().drop_top);
                         cell.set_fg(drop_zone_border);
                     }
                 }
@@ -1131,7 +1274,7 @@ impl Editor {
                 let y = highlight_area.y + highlight_area.height - 1;
                 for x in highlight_area.x..highlight_area.x + highlight_area.width {
                     if let Some(cell) = buf.cell_mut((x, y)) {
-                        cell.set_symbol("▄");
+                        cell.set_symbol(glyphs().drop_bottom);
                         cell.set_fg(drop_zone_border);
                     }
                 }
@@ -1291,7 +1434,12 @@ impl Editor {
         // Get the server command for display
         let server_info = if let Some(lsp) = &self.lsp {
             if let Some(config) = lsp.get_config(language) {
-                format!("{} ({})", language, config.command)
+                let commands: Vec<&str> = config
+                    .servers()
+                    .iter()
+                    .map(|server| server.command.as_str())
+                    .collect();
+                format!("{} ({})", language, commands.join(", "))
             } else {
                 language.to_string()
             }
@@ -1477,17 +1625,24 @@ impl Editor {
             return;
         };
 
-        // Send didOpen to LSP
+        // Send didOpen to each of the language's servers
         if let Some(lsp) = &mut self.lsp {
-            if let Some(client) = lsp.get_or_spawn(language) {
-                tracing::info!("Sending didOpen to newly started LSP for: {}", uri.as_str());
-                if let Err(e) = client.did_open(uri.clone(), text, file_language) {
+            for (index, (label, client)) in lsp.get_or_spawn_all(language).into_iter().enumerate() {
+                tracing::info!(
+                    "Sending didOpen to newly started LSP ({}) for: {}",
+                    label,
+                    uri.as_str()
+                );
+                if let Err(e) = client.did_open(uri.clone(), text.clone(), file_language.clone()) {
                     tracing::warn!("Failed to send didOpen to LSP: {}", e);
                 } else {
                     tracing::info!("Successfully sent didOpen to LSP after confirmation");
 
                     // Request pull diagnostics
-                    let previous_result_id = self.diagnostic_result_ids.get(uri.as_str()).cloned();
+                    let previous_result_id = self
+                        .diagnostic_result_ids
+                        .get(&(label.to_string(), uri.as_str().to_string()))
+                        .cloned();
                     let request_id = self.next_lsp_request_id;
                     self.next_lsp_request_id += 1;
 
@@ -1500,8 +1655,8 @@ impl Editor {
                         );
                     }
 
-                    // Request inlay hints if enabled
-                    if self.config.editor.enable_inlay_hints {
+                    // Request inlay hints if enabled, of the primary server
+                    if index == 0 && self.config.editor.enable_inlay_hints {
                         let request_id = self.next_lsp_request_id;
                         self.next_lsp_request_id += 1;
                         self.pending_inlay_hints_request = Some(request_id);
@@ -1585,63 +1740,32 @@ impl Editor {
     // NOTE: Diagnostics are now applied automatically via process_async_messages()
     // when received from the LSP server asynchronously. No manual polling needed!
 
-    /// Collect all LSP text document changes from an event (recursively for batches)
-    pub(super) fn collect_lsp_changes(&self, event: &Event) -> Vec<TextDocumentContentChangeEvent> {
-        match event {
-            Event::Insert { position, text, .. } => {
-                tracing::trace!(
-                    "collect_lsp_changes: processing Insert at position {}",
-                    position
-                );
-                // For insert: create a zero-width range at the insertion point
-                let (line, character) = self
-                    .active_state()
-                    .buffer
-                    .position_to_lsp_position(*position);
-                let lsp_pos = Position::new(line as u32, character as u32);
-                let lsp_range = LspRange::new(lsp_pos, lsp_pos);
-                vec![TextDocumentContentChangeEvent {
-                    range: Some(lsp_range),
-                    range_length: None,
-                    text: text.clone(),
-                }]
-            }
-            Event::Delete { range, .. } => {
-                tracing::trace!("collect_lsp_changes: processing Delete range {:?}", range);
-                // For delete: create a range from start to end, send empty string
-                let (start_line, start_char) = self
-                    .active_state()
-                    .buffer
-                    .position_to_lsp_position(range.start);
-                let (end_line, end_char) = self
-                    .active_state()
-                    .buffer
-                    .position_to_lsp_position(range.end);
-                let lsp_range = LspRange::new(
-                    Position::new(start_line as u32, start_char as u32),
-                    Position::new(end_line as u32, end_char as u32),
-                );
-                vec![TextDocumentContentChangeEvent {
-                    range: Some(lsp_range),
-                    range_length: None,
-                    text: String::new(),
-                }]
-            }
-            Event::Batch { events, .. } => {
-                // Collect all changes from sub-events into a single vector
-                // This allows sending all changes in one didChange notification
-                tracing::trace!(
-                    "collect_lsp_changes: processing Batch with {} events",
-                    events.len()
-                );
-                let mut all_changes = Vec::new();
-                for sub_event in events {
-                    all_changes.extend(self.collect_lsp_changes(sub_event));
+    /// Collect the LSP text document changes of an event (recursively for
+    /// batches), with positions in the active buffer before it's applied
+    pub(super) fn collect_lsp_changes(&self, event: &Event) -> DocumentChanges {
+        fn collect_edits<'a>(event: &'a Event, edits: &mut Vec<(Range<usize>, &'a str)>) -> bool {
+            match event {
+                Event::Insert { position, text, .. } => {
+                    edits.push((*position..*position, text.as_str()));
                 }
-                all_changes
+                Event::Delete { range, .. } => edits.push((range.clone(), "")),
+                Event::Batch { events, .. } => {
+                    return events.iter().all(|event| collect_edits(event, edits));
+                }
+                // Swaps the whole tree, which only the whole text describes
+                Event::BulkEdit { .. } => return false,
+                _ => {} // Ignore cursor movements and other events
             }
-            _ => Vec::new(), // Ignore cursor movements and other events
+            true
+        }
+
+        let mut edits = Vec::new();
+        if !collect_edits(event, &mut edits) {
+            return DocumentChanges::Whole;
         }
+        tracing::trace!("collect_lsp_changes: {} edits", edits.len());
+        let encoding = self.lsp_position_encoding(self.active_buffer());
+        range_changes(&self.active_state().buffer, edits, encoding)
     }
 
     /// Calculate line information for an event (before buffer modification)
@@ -1728,15 +1852,15 @@ impl Editor {
     }
 
     /// Notify LSP of a file save
-    pub(super) fn notify_lsp_save(&mut self) {
+    pub(super) fn notify_lsp_save(&mut self, buffer_id: BufferId) {
+        // The server's copy is brought up to date before it hears of the save
+        self.flush_lsp_sync(buffer_id);
+
         // Check if LSP is enabled for this buffer
-        let metadata = match self.buffer_metadata.get(&self.active_buffer()) {
+        let metadata = match self.buffer_metadata.get(&buffer_id) {
             Some(m) => m,
             None => {
-                tracing::debug!(
-                    "notify_lsp_save: no metadata for buffer {:?}",
-                    self.active_buffer()
-                );
+                tracing::debug!("notify_lsp_save: no metadata for buffer {:?}", buffer_id);
                 return;
             }
         };
@@ -1773,7 +1897,11 @@ impl Editor {
         };
 
         // Get the full text to send with didSave
-        let full_text = match self.active_state().buffer.to_string() {
+        let full_text = match self
+            .buffers
+            .get(&buffer_id)
+            .and_then(|state| state.buffer.to_string())
+        {
             Some(t) => t,
             None => {
                 tracing::debug!("notify_lsp_save: buffer not fully loaded");
@@ -1787,14 +1915,16 @@ impl Editor {
         );
 
         if let Some(lsp) = &mut self.lsp {
-            if let Some(client) = lsp.get_or_spawn(&language) {
-                // Send didSave with the full text content
-                if let Err(e) = client.did_save(uri, Some(full_text)) {
-                    tracing::warn!("Failed to send didSave to LSP: {}", e);
+            let servers = lsp.get_or_spawn_all(&language);
+            // Send didSave with the full text content, to every server
+            for (label, client) in &servers {
+                if let Err(e) = client.did_save(uri.clone(), Some(full_text.clone())) {
+                    tracing::warn!("Failed to send didSave to LSP ({}): {}", label, e);
                 } else {
-                    tracing::info!("Successfully sent didSave to LSP");
+                    tracing::info!("Successfully sent didSave to LSP ({})", label);
                 }
-            } else {
+            }
+            if servers.is_empty() {
                 tracing::warn!(
                     "notify_lsp_save: failed to get or spawn LSP client for {}",
                     language
@@ -1808,6 +1938,10 @@ impl Editor {
     /// Convert an action into a list of events to apply to the active buffer
     /// Returns None for actions that don't generate events (like Quit)
     pub fn action_to_events(&mut self, action: Action) -> Option<Vec<Event>> {
+        if let Some(events) = self.prose_action_events(&action) {
+            return Some(events);
+        }
+
         let tab_size = self.config.editor.tab_size;
         let auto_indent = self.config.editor.auto_indent;
         let estimated_line_length = self.config.editor.estimated_line_length;
@@ -1832,14 +1966,49 @@ impl Editor {
 
     // === Search and Replace Methods ===
 
+    /// Search of the focused split
+    pub(super) fn active_search(&self) -> Option<&SearchState> {
+        self.split_view_states
+            .get(&self.split_manager.active_split())
+            .and_then(|vs| vs.search.as_ref())
+    }
+
+    /// Replace the search of the focused split
+    pub(super) fn set_active_search(&mut self, search: Option<SearchState>) {
+        let active_split = self.split_manager.active_split();
+        if let Some(view_state) = self.split_view_states.get_mut(&active_split) {
+            view_state.search = search;
+        }
+    }
+
     /// Clear all search highlights from the active buffer
     pub(super) fn clear_search_highlights(&mut self) {
-        let ns = self.search_namespace.clone();
+        let ns = search_namespace(self.split_manager.active_split());
         let state = self.active_state_mut();
         state.overlays.clear_namespace(&ns, &mut state.marker_list);
 
         // Also clear search state
-        self.search_state = None;
+        self.set_active_search(None);
+    }
+
+    /// Clear the focused split's search and every search made in a buffer,
+    /// since an edit to the buffer leaves their match positions stale
+    pub(super) fn clear_searches_in_buffer(&mut self, buffer_id: BufferId) {
+        self.clear_search_highlights();
+
+        for (split_id, view_state) in self.split_view_states.iter_mut() {
+            if view_state
+                .search
+                .as_ref()
+                .is_some_and(|search| search.buffer_id == buffer_id)
+            {
+                view_state.search = None;
+                if let Some(state) = self.buffers.get_mut(&buffer_id) {
+                    let ns = search_namespace(*split_id);
+                    state.overlays.clear_namespace(&ns, &mut state.marker_list);
+                }
+            }
+        }
     }
 
     /// Update search highlights in visible viewport only (for incremental search)
@@ -1851,53 +2020,54 @@ impl Editor {
             return;
         }
 
-        // Get theme colors and search settings before borrowing state
-        let search_bg = self.theme.search_match_bg;
-        let search_fg = self.theme.search_match_fg;
-        let case_sensitive = self.search_case_sensitive;
-        let whole_word = self.search_whole_word;
-        let use_regex = self.search_use_regex;
-        let ns = self.search_namespace.clone();
+        let active_split = self.split_manager.active_split();
+        let highlighted = self.highlight_search_in_split(
+            active_split,
+            query,
+            self.search_is_case_sensitive(query),
+            self.search_whole_word,
+            self.search_use_regex,
+        );
+        if !highlighted {
+            // Invalid regex
+            self.clear_search_highlights();
+        }
+    }
 
-        // Build regex pattern if regex mode is enabled, or escape for literal search
-        let regex_pattern = if use_regex {
-            if whole_word {
-                format!(r"\b{}\b", query)
-            } else {
-                query.to_string()
-            }
-        } else {
-            let escaped = regex::escape(query);
-            if whole_word {
-                format!(r"\b{}\b", escaped)
-            } else {
-                escaped
-            }
+    /// Highlight the matches of a query in the visible part of a split.
+    /// Returns false if the query is not a valid pattern.
+    fn highlight_search_in_split(
+        &mut self,
+        split_id: SplitId,
+        query: &str,
+        case_sensitive: bool,
+        whole_word: bool,
+        use_regex: bool,
+    ) -> bool {
+        let Some(buffer_id) = self.split_manager.buffer_for_split(split_id) else {
+            return true;
         };
 
-        // Build regex with case sensitivity
-        let regex = regex::RegexBuilder::new(&regex_pattern)
-            .case_insensitive(!case_sensitive)
-            .build();
+        // Get theme colors before borrowing state
+        let search_bg = self.theme.search_match_bg;
+        let search_fg = self.theme.search_match_fg;
+        let ns = search_namespace(split_id);
 
-        let regex = match regex {
+        let regex = match build_search_regex(query, case_sensitive, whole_word, use_regex) {
             Ok(r) => r,
-            Err(_) => {
-                // Invalid regex, clear highlights and return
-                self.clear_search_highlights();
-                return;
-            }
+            Err(_) => return false,
         };
 
-        // Get viewport from active split's SplitViewState
-        let active_split = self.split_manager.active_split();
+        // Get viewport from the split's SplitViewState
         let (top_byte, visible_height) = self
             .split_view_states
-            .get(&active_split)
+            .get(&split_id)
             .map(|vs| (vs.viewport.top_byte, vs.viewport.height.saturating_sub(2)))
             .unwrap_or((0, 20));
 
-        let state = self.active_state_mut();
+        let Some(state) = self.buffers.get_mut(&buffer_id) else {
+            return true;
+        };
 
         // Clear any existing search highlights
         state.overlays.clear_namespace(&ns, &mut state.marker_list);
@@ -1940,8 +2110,45 @@ impl Editor {
             )
             .with_priority_value(10); // Priority - above syntax highlighting
 
-            state.overlays.add(overlay);
+            state.overlays.add(overlay, &state.marker_list);
         }
+
+        true
+    }
+
+    /// Find all matches of a query in a buffer, within `search_range` if given.
+    /// Returns the message to show if the buffer cannot be searched.
+    fn find_search_matches(
+        &self,
+        buffer_id: BufferId,
+        query: &str,
+        search_range: Option<&Range<usize>>,
+        case_sensitive: bool,
+        whole_word: bool,
+        use_regex: bool,
+    ) -> Result<Vec<usize>, String> {
+        let buffer_content = self
+            .buffers
+            .get(&buffer_id)
+            .and_then(|state| state.buffer.to_string())
+            .ok_or_else(|| t!("error.buffer_not_loaded").to_string())?;
+
+        // Determine search boundaries
+        let (search_start, search_end) = if let Some(range) = search_range {
+            (range.start, range.end)
+        } else {
+            (0, buffer_content.len())
+        };
+
+        let regex = build_search_regex(query, case_sensitive, whole_word, use_regex)
+            .map_err(|e| t!("error.invalid_regex", error = e.to_string()).to_string())?;
+
+        // Find all matches within the search range
+        let search_slice = &buffer_content[search_start..search_end];
+        Ok(regex
+            .find_iter(search_slice)
+            .map(|m| search_start + m.start())
+            .collect())
     }
 
     /// Perform a search and update search state
@@ -1953,76 +2160,37 @@ impl Editor {
         // 3. User starts a new search (update_search_highlights clears old ones)
 
         if query.is_empty() {
-            self.search_state = None;
+            self.set_active_search(None);
             self.set_status_message(t!("search.cancelled").to_string());
             return;
         }
 
         let search_range = self.pending_search_range.take();
-
-        let buffer_content = {
-            let state = self.active_state();
-            match state.buffer.to_string() {
-                Some(t) => t,
-                None => {
-                    self.set_status_message(t!("error.buffer_not_loaded").to_string());
-                    return;
-                }
-            }
-        };
+        let buffer_id = self.active_buffer();
 
         // Get search settings
-        let case_sensitive = self.search_case_sensitive;
+        let case_sensitive = self.search_is_case_sensitive(query);
         let whole_word = self.search_whole_word;
         let use_regex = self.search_use_regex;
 
-        // Determine search boundaries
-        let (search_start, search_end) = if let Some(ref range) = search_range {
-            (range.start, range.end)
-        } else {
-            (0, buffer_content.len())
-        };
-
-        // Build regex pattern
-        let regex_pattern = if use_regex {
-            if whole_word {
-                format!(r"\b{}\b", query)
-            } else {
-                query.to_string()
-            }
-        } else {
-            let escaped = regex::escape(query);
-            if whole_word {
-                format!(r"\b{}\b", escaped)
-            } else {
-                escaped
-            }
-        };
-
-        // Build regex with case sensitivity
-        let regex = match regex::RegexBuilder::new(&regex_pattern)
-            .case_insensitive(!case_sensitive)
-            .build()
-        {
-            Ok(r) => r,
-            Err(e) => {
-                self.search_state = None;
-                self.set_status_message(
-                    t!("error.invalid_regex", error = e.to_string()).to_string(),
-                );
+        let matches = match self.find_search_matches(
+            buffer_id,
+            query,
+            search_range.as_ref(),
+            case_sensitive,
+            whole_word,
+            use_regex,
+        ) {
+            Ok(matches) => matches,
+            Err(message) => {
+                self.set_active_search(None);
+                self.set_status_message(message);
                 return;
             }
         };
 
-        // Find all matches within the search range
-        let search_slice = &buffer_content[search_start..search_end];
-        let matches: Vec<usize> = regex
-            .find_iter(search_slice)
-            .map(|m| search_start + m.start())
-            .collect();
-
         if matches.is_empty() {
-            self.search_state = None;
+            self.set_active_search(None);
             let msg = if search_range.is_some() {
                 format!("No matches found for '{}' in selection", query)
             } else {
@@ -2060,17 +2228,22 @@ impl Editor {
         }
 
         let num_matches = matches.len();
+        let in_selection = search_range.is_some();
 
         // Update search state
-        self.search_state = Some(SearchState {
+        self.set_active_search(Some(SearchState {
             query: query.to_string(),
+            buffer_id,
             matches,
             current_match_index: Some(current_match_index),
             wrap_search: search_range.is_none(), // Only wrap if not searching in selection
             search_range,
-        });
+            case_sensitive,
+            whole_word,
+            use_regex,
+        }));
 
-        let msg = if self.search_state.as_ref().unwrap().search_range.is_some() {
+        let msg = if in_selection {
             format!(
                 "Found {} match{} for '{}' in selection",
                 num_matches,
@@ -2088,21 +2261,112 @@ impl Editor {
         self.set_status_message(msg);
     }
 
+    /// Search a split's buffer without moving its cursor; Find Next/Previous
+    /// then go to the matches around the cursor. Returns None if nothing matches.
+    pub(super) fn search_in_split(
+        &self,
+        split_id: SplitId,
+        query: &str,
+        case_sensitive: bool,
+        whole_word: bool,
+        use_regex: bool,
+    ) -> Option<SearchState> {
+        let buffer_id = self.split_manager.buffer_for_split(split_id)?;
+        let matches = self
+            .find_search_matches(
+                buffer_id,
+                query,
+                None,
+                case_sensitive,
+                whole_word,
+                use_regex,
+            )
+            .ok()
+            .filter(|matches| !matches.is_empty())?;
+
+        Some(SearchState {
+            query: query.to_string(),
+            buffer_id,
+            matches,
+            current_match_index: None,
+            wrap_search: true,
+            search_range: None,
+            case_sensitive,
+            whole_word,
+            use_regex,
+        })
+    }
+
+    /// Give every split the focused split's search ("Search in All Splits")
+    pub(super) fn search_in_all_splits(&mut self) {
+        let Some(search) = self.active_search() else {
+            self.set_status_message(t!("search.no_active").to_string());
+            return;
+        };
+        let query = search.query.clone();
+        let (case_sensitive, whole_word, use_regex) =
+            (search.case_sensitive, search.whole_word, search.use_regex);
+
+        let active_split = self.split_manager.active_split();
+        let mut searched = 1;
+        for split_id in self.split_manager.root().leaf_split_ids() {
+            if split_id == active_split {
+                continue;
+            }
+            let search =
+                self.search_in_split(split_id, &query, case_sensitive, whole_word, use_regex);
+            if search.is_some() {
+                searched += 1;
+            } else if let Some(state) = self
+                .split_manager
+                .buffer_for_split(split_id)
+                .and_then(|buffer_id| self.buffers.get_mut(&buffer_id))
+            {
+                // Drop the highlights of the split's previous search
+                let ns = search_namespace(split_id);
+                state.overlays.clear_namespace(&ns, &mut state.marker_list);
+            }
+            if let Some(view_state) = self.split_view_states.get_mut(&split_id) {
+                view_state.search = search;
+            }
+        }
+
+        self.set_status_message(
+            t!("search.all_splits", query = query, count = searched).to_string(),
+        );
+    }
+
     /// Find the next match
     pub(super) fn find_next(&mut self) {
-        if let Some(ref mut search_state) = self.search_state {
+        let cursor_pos = self.active_state().cursors.primary().position;
+        let active_split = self.split_manager.active_split();
+        if let Some(search_state) = self
+            .split_view_states
+            .get_mut(&active_split)
+            .and_then(|vs| vs.search.as_mut())
+        {
             if search_state.matches.is_empty() {
                 return;
             }
 
-            let current_index = search_state.current_match_index.unwrap_or(0);
-            let next_index = if current_index + 1 < search_state.matches.len() {
-                current_index + 1
-            } else if search_state.wrap_search {
-                0 // Wrap to beginning
-            } else {
-                self.set_status_message(t!("search.no_matches").to_string());
-                return;
+            let next_index = match search_state.current_match_index {
+                Some(current_index) if current_index + 1 < search_state.matches.len() => {
+                    Some(current_index + 1)
+                }
+                Some(_) => None,
+                // No match visited yet: the first one after the cursor
+                None => search_state
+                    .matches
+                    .iter()
+                    .position(|&pos| pos > cursor_pos),
+            };
+            let next_index = match next_index {
+                Some(next_index) => next_index,
+                None if search_state.wrap_search => 0, // Wrap to beginning
+                None => {
+                    self.set_status_message(t!("search.no_matches").to_string());
+                    return;
+                }
             };
 
             search_state.current_match_index = Some(next_index);
@@ -2110,7 +2374,6 @@ impl Editor {
             let matches_len = search_state.matches.len();
 
             {
-                let active_split = self.split_manager.active_split();
                 let active_buffer = self.active_buffer();
                 let state = self.active_state_mut();
                 state.cursors.primary_mut().position = match_pos;
@@ -2139,19 +2402,32 @@ impl Editor {
 
     /// Find the previous match
     pub(super) fn find_previous(&mut self) {
-        if let Some(ref mut search_state) = self.search_state {
+        let cursor_pos = self.active_state().cursors.primary().position;
+        let active_split = self.split_manager.active_split();
+        if let Some(search_state) = self
+            .split_view_states
+            .get_mut(&active_split)
+            .and_then(|vs| vs.search.as_mut())
+        {
             if search_state.matches.is_empty() {
                 return;
             }
 
-            let current_index = search_state.current_match_index.unwrap_or(0);
-            let prev_index = if current_index > 0 {
-                current_index - 1
-            } else if search_state.wrap_search {
-                search_state.matches.len() - 1 // Wrap to end
-            } else {
-                self.set_status_message(t!("search.no_matches").to_string());
-                return;
+            let prev_index = match search_state.current_match_index {
+                Some(current_index) => current_index.checked_sub(1),
+                // No match visited yet: the last one before the cursor
+                None => search_state
+                    .matches
+                    .iter()
+                    .rposition(|&pos| pos < cursor_pos),
+            };
+            let prev_index = match prev_index {
+                Some(prev_index) => prev_index,
+                None if search_state.wrap_search => search_state.matches.len() - 1, // Wrap to end
+                None => {
+                    self.set_status_message(t!("search.no_matches").to_string());
+                    return;
+                }
             };
 
             search_state.current_match_index = Some(prev_index);
@@ -2159,7 +2435,6 @@ impl Editor {
             let matches_len = search_state.matches.len();
 
             {
-                let active_split = self.split_manager.active_split();
                 let active_buffer = self.active_buffer();
                 let state = self.active_state_mut();
                 state.cursors.primary_mut().position = match_pos;
@@ -2194,7 +2469,7 @@ impl Editor {
     /// Otherwise, it starts a new search with the current selection or word under cursor.
     pub(super) fn find_selection_next(&mut self) {
         // If there's already a search active, just continue to next match
-        if self.search_state.is_some() {
+        if self.active_search().is_some() {
             self.find_next();
             return;
         }
@@ -2211,7 +2486,7 @@ impl Editor {
                 self.perform_search(&text);
 
                 // Check if we need to move to next match
-                if let Some(ref search_state) = self.search_state {
+                if let Some(search_state) = self.active_search() {
                     let cursor_after = self.active_state().cursors.primary().position;
 
                     // If we started at a match (selection_start matches a search result),
@@ -2248,7 +2523,7 @@ impl Editor {
     /// Otherwise, it starts a new search with the current selection or word under cursor.
     pub(super) fn find_selection_previous(&mut self) {
         // If there's already a search active, just continue to previous match
-        if self.search_state.is_some() {
+        if self.active_search().is_some() {
             self.find_previous();
             return;
         }
@@ -2265,7 +2540,7 @@ impl Editor {
                 self.perform_search(&text);
 
                 // If we found matches, navigate to previous
-                if let Some(ref search_state) = self.search_state {
+                if let Some(search_state) = self.active_search() {
                     let cursor_after = self.active_state().cursors.primary().position;
 
                     // Check if we started at a match
@@ -2352,6 +2627,7 @@ impl Editor {
         }
 
         // Find all matches first (before making any modifications)
+        let matcher = self.replace_matcher(search);
         let matches = {
             let state = self.active_state();
             let buffer_len = state.buffer.len();
@@ -2359,13 +2635,9 @@ impl Editor {
             let mut current_pos = 0;
 
             while current_pos < buffer_len {
-                if let Some(offset) = state.buffer.find_next_in_range(
-                    search,
-                    current_pos,
-                    Some(current_pos..buffer_len),
-                ) {
-                    matches.push(offset);
-                    current_pos = offset + search.len();
+                if let Some(found) = matcher.find(&state.buffer, current_pos..buffer_len) {
+                    current_pos = found.end;
+                    matches.push(found);
                 } else {
                     break;
                 }
@@ -2386,17 +2658,19 @@ impl Editor {
         // Create Delete+Insert events for each match
         // Events will be processed in reverse order by apply_events_as_bulk_edit
         let mut events = Vec::with_capacity(count * 2);
-        for &match_pos in &matches {
+        for found in matches {
+            let matched = self.replace_match_text(&matcher, found.clone());
+            let text = matcher.replacement_for(&matched, replacement);
             // Delete the matched text
             events.push(Event::Delete {
-                range: match_pos..match_pos + search.len(),
-                deleted_text: search.to_string(), // We know what text is being deleted
+                range: found.clone(),
+                deleted_text: matched,
                 cursor_id,
             });
             // Insert the replacement
             events.push(Event::Insert {
-                position: match_pos,
-                text: replacement.to_string(),
+                position: found.start,
+                text,
                 cursor_id,
             });
         }
@@ -2404,16 +2678,12 @@ impl Editor {
         // Apply all replacements using BulkEdit for O(n) performance
         let description = format!("Replace all '{}' with '{}'", search, replacement);
         if let Some(bulk_edit) = self.apply_events_as_bulk_edit(events, description) {
-            self.active_event_log_mut().append(bulk_edit);
+            let label = t!("undo.replace", count = count).to_string();
+            self.active_event_log_mut().append_labeled(bulk_edit, label);
         }
 
-        // Clear search state since positions are now invalid
-        self.search_state = None;
-
-        // Clear any search highlight overlays
-        let ns = self.search_namespace.clone();
-        let state = self.active_state_mut();
-        state.overlays.clear_namespace(&ns, &mut state.marker_list);
+        // Clear search state and highlights since positions are now invalid
+        self.clear_search_highlights();
 
         // Set status message
         self.set_status_message(
@@ -2435,20 +2705,27 @@ impl Editor {
         }
 
         // Find the first match lazily (don't find all matches upfront)
+        let matcher = self.replace_matcher(search);
         let state = self.active_state();
         let start_pos = state.cursors.primary().position;
-        let first_match = state.buffer.find_next(search, start_pos);
+        let buffer_len = state.buffer.len();
+        let first_match = matcher
+            .find(&state.buffer, start_pos..buffer_len)
+            .or_else(|| matcher.find(&state.buffer, 0..start_pos));
 
-        let Some(first_match_pos) = first_match else {
+        let Some(first_match) = first_match else {
             self.set_status_message(t!("search.no_occurrences", search = search).to_string());
             return;
         };
+        let first_match_pos = first_match.start;
 
         // Initialize interactive replace state with just the current match
         self.interactive_replace_state = Some(InteractiveReplaceState {
             search: search.to_string(),
             replacement: replacement.to_string(),
+            matcher,
             current_match_pos: first_match_pos,
+            current_match_len: first_match.len(),
             start_pos: first_match_pos,
             has_wrapped: false,
             replacements_made: 0,
@@ -2487,15 +2764,16 @@ impl Editor {
         match c {
             'y' | 'Y' => {
                 // Replace current match
-                self.replace_current_match(&ir_state)?;
+                let replaced_len = self.replace_current_match(&ir_state)?;
                 ir_state.replacements_made += 1;
 
                 // Find next match lazily (after the replacement)
-                let search_pos = ir_state.current_match_pos + ir_state.replacement.len();
+                let search_pos = ir_state.current_match_pos + replaced_len;
                 if let Some((next_match, wrapped)) =
                     self.find_next_match_for_replace(&ir_state, search_pos)
                 {
-                    ir_state.current_match_pos = next_match;
+                    ir_state.current_match_pos = next_match.start;
+                    ir_state.current_match_len = next_match.len();
                     if wrapped {
                         ir_state.has_wrapped = true;
                     }
@@ -2507,11 +2785,12 @@ impl Editor {
             }
             'n' | 'N' => {
                 // Skip current match and find next
-                let search_pos = ir_state.current_match_pos + ir_state.search.len();
+                let search_pos = ir_state.current_match_pos + ir_state.current_match_len;
                 if let Some((next_match, wrapped)) =
                     self.find_next_match_for_replace(&ir_state, search_pos)
                 {
-                    ir_state.current_match_pos = next_match;
+                    ir_state.current_match_pos = next_match.start;
+                    ir_state.current_match_len = next_match.len();
                     if wrapped {
                         ir_state.has_wrapped = true;
                     }
@@ -2536,16 +2815,17 @@ impl Editor {
                     temp_state.has_wrapped = false; // Reset wrap state to find current match
 
                     // First, include the current match
-                    matches.push(ir_state.current_match_pos);
-                    let mut current_pos = ir_state.current_match_pos + ir_state.search.len();
+                    let current_end = ir_state.current_match_pos + ir_state.current_match_len;
+                    matches.push(ir_state.current_match_pos..current_end);
+                    let mut current_pos = current_end;
 
                     // Find all remaining matches
                     loop {
                         if let Some((next_match, wrapped)) =
                             self.find_next_match_for_replace(&temp_state, current_pos)
                         {
+                            current_pos = next_match.end;
                             matches.push(next_match);
-                            current_pos = next_match + temp_state.search.len();
                             if wrapped {
                                 temp_state.has_wrapped = true;
                             }
@@ -2564,15 +2844,19 @@ impl Editor {
 
                     // Create Delete+Insert events for each match
                     let mut events = Vec::with_capacity(total_count * 2);
-                    for &match_pos in &all_matches {
+                    for found in all_matches {
+                        let matched = self.replace_match_text(&ir_state.matcher, found.clone());
+                        let text = ir_state
+                            .matcher
+                            .replacement_for(&matched, &ir_state.replacement);
                         events.push(Event::Delete {
-                            range: match_pos..match_pos + ir_state.search.len(),
-                            deleted_text: ir_state.search.clone(),
+                            range: found.clone(),
+                            deleted_text: matched,
                             cursor_id,
                         });
                         events.push(Event::Insert {
-                            position: match_pos,
-                            text: ir_state.replacement.clone(),
+                            position: found.start,
+                            text,
                             cursor_id,
                         });
                     }
@@ -2583,7 +2867,8 @@ impl Editor {
                         total_count, ir_state.search, ir_state.replacement
                     );
                     if let Some(bulk_edit) = self.apply_events_as_bulk_edit(events, description) {
-                        self.active_event_log_mut().append(bulk_edit);
+                        let label = t!("undo.replace", count = total_count).to_string();
+                        self.active_event_log_mut().append_labeled(bulk_edit, label);
                     }
 
                     ir_state.replacements_made += total_count;
@@ -2608,62 +2893,54 @@ impl Editor {
         &self,
         ir_state: &InteractiveReplaceState,
         start_pos: usize,
-    ) -> Option<(usize, bool)> {
+    ) -> Option<(Range<usize>, bool)> {
         let state = self.active_state();
 
         if ir_state.has_wrapped {
             // We've already wrapped - only search from start_pos up to (but not including) the original start position
-            // Use find_next_in_range to avoid wrapping again
-            let search_range = Some(start_pos..ir_state.start_pos);
-            if let Some(match_pos) =
-                state
-                    .buffer
-                    .find_next_in_range(&ir_state.search, start_pos, search_range)
+            // Search only within that range to avoid wrapping again
+            if let Some(found) = ir_state
+                .matcher
+                .find(&state.buffer, start_pos..ir_state.start_pos)
             {
-                return Some((match_pos, true));
+                return Some((found, true));
             }
             None // No more matches before original start position
         } else {
             // Haven't wrapped yet - search normally from start_pos
             // First try from start_pos to end of buffer
             let buffer_len = state.buffer.len();
-            let search_range = Some(start_pos..buffer_len);
-            if let Some(match_pos) =
-                state
-                    .buffer
-                    .find_next_in_range(&ir_state.search, start_pos, search_range)
-            {
-                return Some((match_pos, false));
+            if let Some(found) = ir_state.matcher.find(&state.buffer, start_pos..buffer_len) {
+                return Some((found, false));
             }
 
             // No match from start_pos to end - wrap to beginning
             // Search from 0 to start_pos (original position)
-            let wrap_range = Some(0..ir_state.start_pos);
-            if let Some(match_pos) =
-                state
-                    .buffer
-                    .find_next_in_range(&ir_state.search, 0, wrap_range)
-            {
-                return Some((match_pos, true)); // Found match after wrapping
+            if let Some(found) = ir_state.matcher.find(&state.buffer, 0..ir_state.start_pos) {
+                return Some((found, true)); // Found match after wrapping
             }
 
             None // No matches found anywhere
         }
     }
 
-    /// Replace the current match in interactive replace mode
+    /// Replace the current match in interactive replace mode, returning the
+    /// length of the text put in its place
     pub(super) fn replace_current_match(
         &mut self,
         ir_state: &InteractiveReplaceState,
-    ) -> std::io::Result<()> {
+    ) -> std::io::Result<usize> {
         let match_pos = ir_state.current_match_pos;
-        let search_len = ir_state.search.len();
-        let range = match_pos..(match_pos + search_len);
+        let range = match_pos..(match_pos + ir_state.current_match_len);
 
         // Get the deleted text for the event
         let deleted_text = self
             .active_state_mut()
             .get_text_range(range.start, range.end);
+        let replacement = ir_state
+            .matcher
+            .replacement_for(&deleted_text, &ir_state.replacement);
+        let replaced_len = replacement.len();
 
         // Capture current cursor state for undo
         let cursor_id = self.active_state().cursors.primary_id();
@@ -2691,7 +2968,7 @@ impl Editor {
             },
             Event::Insert {
                 position: match_pos,
-                text: ir_state.replacement.clone(),
+                text: replacement,
                 cursor_id,
             },
         ];
@@ -2706,10 +2983,12 @@ impl Editor {
         };
 
         // Apply the batch through the event log
-        self.active_event_log_mut().append(batch.clone());
+        let label = t!("undo.replace", count = 1).to_string();
+        self.active_event_log_mut()
+            .append_labeled(batch.clone(), label);
         self.apply_event_to_active_buffer(&batch);
 
-        Ok(())
+        Ok(replaced_len)
     }
 
     /// Move cursor to the current match in interactive replace
@@ -2751,7 +3030,7 @@ impl Editor {
         self.prompt = None; // Clear the query-replace prompt
 
         // Clear search highlights
-        let ns = self.search_namespace.clone();
+        let ns = search_namespace(self.split_manager.active_split());
         let state = self.active_state_mut();
         state.overlays.clear_namespace(&ns, &mut state.marker_list);
 
@@ -2940,7 +3219,14 @@ impl Editor {
         // Use optimized bulk edit for multi-line comment toggle
         let description = format!("{} lines", action_desc);
         if let Some(bulk_edit) = self.apply_events_as_bulk_edit(events, description) {
-            self.active_event_log_mut().append(bulk_edit);
+            let count = line_starts.len();
+            let label = if all_commented {
+                t!("undo.uncomment_lines", count = count)
+            } else {
+                t!("undo.comment_lines", count = count)
+            };
+            self.active_event_log_mut()
+                .append_labeled(bulk_edit, label.to_string());
         }
 
         self.set_status_message(
@@ -3062,16 +3348,8 @@ impl Editor {
         // Get all diagnostic overlay positions
         let mut diagnostic_positions: Vec<usize> = state
             .overlays
-            .all()
-            .iter()
-            .filter_map(|overlay| {
-                // Only consider LSP diagnostics (those in the diagnostic namespace)
-                if overlay.namespace.as_ref() == Some(&diagnostic_ns) {
-                    Some(overlay.range(&state.marker_list).start)
-                } else {
-                    None
-                }
-            })
+            .in_namespace(&diagnostic_ns)
+            .map(|overlay| overlay.range(&state.marker_list).start)
             .collect();
 
         if diagnostic_positions.is_empty() {
@@ -3105,14 +3383,14 @@ impl Editor {
 
             // Show diagnostic message in status bar
             let state = self.active_state();
-            if let Some(msg) = state.overlays.all().iter().find_map(|overlay| {
-                let range = overlay.range(&state.marker_list);
-                if range.start == new_pos && overlay.namespace.as_ref() == Some(&diagnostic_ns) {
-                    overlay.message.clone()
-                } else {
-                    None
-                }
-            }) {
+            if let Some(msg) = state
+                .overlays
+                .in_namespace(&diagnostic_ns)
+                .filter(|overlay| overlay.range(&state.marker_list).start == new_pos)
+                .filter(|overlay| overlay.message.is_some())
+                .min_by_key(|overlay| (overlay.priority, overlay.handle.clone()))
+                .and_then(|overlay| overlay.message.clone())
+            {
                 self.set_status_message(msg);
             }
         }
@@ -3129,16 +3407,8 @@ impl Editor {
         // Get all diagnostic overlay positions
         let mut diagnostic_positions: Vec<usize> = state
             .overlays
-            .all()
-            .iter()
-            .filter_map(|overlay| {
-                // Only consider LSP diagnostics (those in the diagnostic namespace)
-                if overlay.namespace.as_ref() == Some(&diagnostic_ns) {
-                    Some(overlay.range(&state.marker_list).start)
-                } else {
-                    None
-                }
-            })
+            .in_namespace(&diagnostic_ns)
+            .map(|overlay| overlay.range(&state.marker_list).start)
             .collect();
 
         if diagnostic_positions.is_empty() {
@@ -3173,14 +3443,14 @@ impl Editor {
 
             // Show diagnostic message in status bar
             let state = self.active_state();
-            if let Some(msg) = state.overlays.all().iter().find_map(|overlay| {
-                let range = overlay.range(&state.marker_list);
-                if range.start == new_pos && overlay.namespace.as_ref() == Some(&diagnostic_ns) {
-                    overlay.message.clone()
-                } else {
-                    None
-                }
-            }) {
+            if let Some(msg) = state
+                .overlays
+                .in_namespace(&diagnostic_ns)
+                .filter(|overlay| overlay.range(&state.marker_list).start == new_pos)
+                .filter(|overlay| overlay.message.is_some())
+                .min_by_key(|overlay| (overlay.priority, overlay.handle.clone()))
+                .and_then(|overlay| overlay.message.clone())
+            {
                 self.set_status_message(msg);
             }
         }
@@ -3337,7 +3607,13 @@ impl Editor {
             lsp_disabled_reason: Some("Virtual macro buffer".to_string()),
             read_only: false, // Allow editing for saving
             binary: false,
+            fs_read_only: None,
+            edit_anyway: false,
+            protected: None,
             lsp_opened_with: std::collections::HashSet::new(),
+            auto_revert: None,
+            follow_tail: false,
+            pinned: false,
         };
         self.buffer_metadata.insert(buffer_id, metadata);
 
@@ -3411,7 +3687,13 @@ impl Editor {
             lsp_disabled_reason: Some("Virtual macro list buffer".to_string()),
             read_only: true,
             binary: false,
+            fs_read_only: None,
+            edit_anyway: false,
+            protected: None,
             lsp_opened_with: std::collections::HashSet::new(),
+            auto_revert: None,
+            follow_tail: false,
+            pinned: false,
         };
         self.buffer_metadata.insert(buffer_id, metadata);
 
@@ -3516,9 +3798,9 @@ impl Editor {
     /// Save search and replace histories to disk
     /// Called on shutdown to persist history across sessions
     pub fn save_histories(&self) {
-        // Ensure data directory exists
-        if let Err(e) = std::fs::create_dir_all(&self.dir_context.data_dir) {
-            tracing::warn!("Failed to create data directory: {}", e);
+        // Ensure state directory exists
+        if let Err(e) = std::fs::create_dir_all(&self.dir_context.state_dir) {
+            tracing::warn!("Failed to create state directory: {}", e);
             return;
         }
 
@@ -3756,3 +4038,32 @@ impl Editor {
         }
     }
 }
+
+/// Build the regex for a search query with the given options
+fn build_search_regex(
+    query: &str,
+    case_sensitive: bool,
+    whole_word: bool,
+    use_regex: bool,
+) -> Result<regex::Regex, regex::Error> {
+    // Build regex pattern if regex mode is enabled, or escape for literal search
+    let regex_pattern = if use_regex {
+        if whole_word {
+            format!(r"\b{}\b", query)
+        } else {
+            query.to_string()
+        }
+    } else {
+        let escaped = regex::escape(query);
+        if whole_word {
+            format!(r"\b{}\b", escaped)
+        } else {
+            escaped
+        }
+    };
+
+    // Build regex with case sensitivity
+    regex::RegexBuilder::new(&regex_pattern)
+        .case_insensitive(!case_sensitive)
+        .build()
+}
@@ -0,0 +1,610 @@
+//! "Revert Buffer to Saved"
+//!
+//! Unlike "Revert File", which swaps in a freshly loaded buffer and drops the
+//! undo history, this replaces only the lines that differ from the file on
+//! disk, as a single bulk edit. One undo brings the discarded changes back.
+//!
+//! Cursors and each split's scroll position are carried over by line: a
+//! position on a line that is unchanged on disk stays on it, any other
+//! position keeps its distance from the nearest unchanged line above, and
+//! the column is kept where the line allows. The file's encoding and line
+//! ending are detected again, overlays are cleared except in namespaces that
+//! plugins marked with `setRevertStableNamespace`, and the `buffer_reverted`
+//! hook fires afterwards.
+//!
+//! A modified buffer asks for confirmation first; the prompt can open a
+//! read-only preview of the lines that would change.
+
+use std::ops::Range;
+use std::path::Path;
+
+use rust_i18n::t;
+
+use crate::model::event::{BufferId, Event};
+use crate::model::line_diff::matching_lines;
+use crate::primitives::text_property::TextPropertyEntry;
+use crate::services::plugins::hooks::HookArgs;
+use crate::view::prompt::PromptType;
+
+use super::Editor;
+
+/// Name of the buffer showing what a revert would change
+const PREVIEW_BUFFER_NAME: &str = "*Revert Preview*";
+
+/// A run of lines that differs between the buffer and the file on disk
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct Hunk {
+    /// Lines of the buffer that are replaced
+    pub(super) old_lines: Range<usize>,
+    /// Lines of the file that replace them
+    pub(super) new_lines: Range<usize>,
+    /// Bytes of the buffer that are replaced
+    pub(super) old_bytes: Range<usize>,
+    /// Bytes of the file that replace them
+    pub(super) new_bytes: Range<usize>,
+}
+
+/// Start offset of every line split on `\n`, followed by `text.len() + 1`
+///
+/// The last line ends at a virtual newline past the end of the text, so every
+/// line `i` spans `starts[i]..starts[i + 1]` including its newline.
+pub(super) fn line_starts(text: &[u8]) -> Vec<usize> {
+    let mut starts = vec![0];
+    starts.extend(
+        text.iter()
+            .enumerate()
+            .filter(|&(_, &b)| b == b'\n')
+            .map(|(i, _)| i + 1),
+    );
+    starts.push(text.len() + 1);
+    starts
+}
+
+/// Hunks turning `old` into the new text, in ascending order
+///
+/// `old_starts` and `new_starts` come from [`line_starts`] and `matches` from
+/// [`matching_lines`]. Only the final boundary runs past the end of either
+/// text, so checking `old` for the virtual newline covers both.
+pub(super) fn diff_hunks(
+    old: &[u8],
+    old_starts: &[usize],
+    new_starts: &[usize],
+    matches: &[(usize, usize)],
+) -> Vec<Hunk> {
+    let old_count = old_starts.len() - 1;
+    let new_count = new_starts.len() - 1;
+
+    let mut hunks = Vec::new();
+    let (mut old_line, mut new_line) = (0, 0);
+    let boundaries = matches
+        .iter()
+        .copied()
+        .chain(std::iter::once((old_count, new_count)));
+    for (old_match, new_match) in boundaries {
+        if old_line < old_match || new_line < new_match {
+            let old_lines = old_line..old_match;
+            let new_lines = new_line..new_match;
+            let mut old_bytes = old_starts[old_lines.start]..old_starts[old_lines.end];
+            let mut new_bytes = new_starts[new_lines.start]..new_starts[new_lines.end];
+
+            // Drop the virtual newline after the last line
+            if old_bytes.end > old.len() {
+                if old_bytes.is_empty() || new_bytes.is_empty() {
+                    // Insert or delete the newline before the run instead
+                    old_bytes = old_bytes.start - 1..old_bytes.end - 1;
+                    new_bytes = new_bytes.start - 1..new_bytes.end - 1;
+                } else {
+                    old_bytes.end -= 1;
+                    new_bytes.end -= 1;
+                }
+            }
+
+            hunks.push(Hunk {
+                old_lines,
+                new_lines,
+                old_bytes,
+                new_bytes,
+            });
+        }
+        old_line = old_match + 1;
+        new_line = new_match + 1;
+    }
+    hunks
+}
+
+/// Maps byte offsets in the buffer to offsets in the reverted text
+struct OffsetMap<'a> {
+    new: &'a [u8],
+    old_starts: &'a [usize],
+    new_starts: &'a [usize],
+    /// Unchanged lines as `(buffer line, file line)`, from [`matching_lines`]
+    matches: &'a [(usize, usize)],
+}
+
+impl OffsetMap<'_> {
+    /// Offset in the reverted text for `offset` in the buffer
+    ///
+    /// A line counts from the closest unchanged line at or above it, so
+    /// positions follow lines added or removed elsewhere. The column is kept
+    /// where the line allows.
+    fn map(&self, offset: usize) -> usize {
+        let old_line = self.old_starts.partition_point(|&start| start <= offset) - 1;
+        let column = offset - self.old_starts[old_line];
+
+        let new_count = self.new_starts.len() - 1;
+        let above = self.matches.partition_point(|&(old, _)| old <= old_line);
+        let new_line = match above.checked_sub(1).map(|i| self.matches[i]) {
+            Some((old, new)) => new + (old_line - old),
+            None => old_line,
+        }
+        .min(new_count - 1);
+        let line_start = self.new_starts[new_line];
+        let line_len = (self.new_starts[new_line + 1] - 1) - line_start;
+
+        let mut mapped = line_start + column.min(line_len);
+        while mapped > 0 && mapped < self.new.len() && (self.new[mapped] & 0xC0) == 0x80 {
+            mapped -= 1;
+        }
+        mapped
+    }
+}
+
+impl Editor {
+    /// Revert the active buffer to its file on disk as an undoable edit
+    ///
+    /// Asks first if the buffer has unsaved changes.
+    pub fn revert_buffer_to_saved(&mut self) {
+        let buffer_id = self.active_buffer();
+        let has_file = self
+            .buffer_metadata
+            .get(&buffer_id)
+            .is_some_and(|m| !m.is_virtual() && m.file_path().is_some());
+        if !has_file {
+            self.set_status_message(t!("status.revert_to_saved_unavailable").to_string());
+            return;
+        }
+
+        // The buffer holds the file without its escape sequences: reload it
+        // rather than diff against it
+        if self.is_showing_ansi_colors(buffer_id) {
+            if let Err(e) = self.revert_file() {
+                self.set_status_message(
+                    t!("error.failed_to_revert", error = e.to_string()).to_string(),
+                );
+            }
+            return;
+        }
+
+        if self.active_state().buffer.is_modified() {
+            self.ask_revert_to_saved(buffer_id, None);
+        } else {
+            self.apply_revert_to_saved(buffer_id);
+        }
+    }
+
+    fn ask_revert_to_saved(&mut self, buffer_id: BufferId, preview: Option<BufferId>) {
+        self.start_prompt(
+            t!(
+                "prompt.revert_to_saved_confirm",
+                revert_key = t!("prompt.key.revert"),
+                preview_key = t!("prompt.key.preview"),
+                cancel_key = t!("prompt.key.cancel")
+            )
+            .to_string(),
+            PromptType::ConfirmRevertToSaved { buffer_id, preview },
+        );
+    }
+
+    /// Answer to "Revert Buffer to Saved" for a modified buffer
+    pub(super) fn handle_confirm_revert_to_saved(
+        &mut self,
+        input: &str,
+        buffer_id: BufferId,
+        preview: Option<BufferId>,
+    ) {
+        let input_lower = input.trim().to_lowercase();
+        let revert_key = t!("prompt.key.revert").to_string().to_lowercase();
+        let preview_key = t!("prompt.key.preview").to_string().to_lowercase();
+
+        if input_lower == revert_key || input_lower == "revert" {
+            self.close_revert_preview(buffer_id, preview);
+            self.apply_revert_to_saved(buffer_id);
+        } else if input_lower == preview_key || input_lower == "preview" {
+            match self.show_revert_preview(buffer_id, preview) {
+                Ok(preview) => self.ask_revert_to_saved(buffer_id, Some(preview)),
+                Err(e) => {
+                    self.close_revert_preview(buffer_id, preview);
+                    self.set_status_message(
+                        t!("error.failed_to_revert", error = e.to_string()).to_string(),
+                    );
+                }
+            }
+        } else {
+            self.close_revert_preview(buffer_id, preview);
+            self.set_status_message(t!("buffer.revert_cancelled").to_string());
+        }
+    }
+
+    /// Close the revert preview, if open, and go back to the buffer
+    pub(super) fn close_revert_preview(&mut self, buffer_id: BufferId, preview: Option<BufferId>) {
+        let Some(preview) = preview else {
+            return;
+        };
+        if self.buffers.contains_key(&buffer_id) {
+            self.set_active_buffer(buffer_id);
+        }
+        if self.buffers.contains_key(&preview) {
+            if let Err(e) = self.close_buffer(preview) {
+                tracing::warn!("Failed to close revert preview: {}", e);
+            }
+        }
+    }
+
+    /// Show the lines a revert would change in a read-only buffer
+    fn show_revert_preview(
+        &mut self,
+        buffer_id: BufferId,
+        preview: Option<BufferId>,
+    ) -> std::io::Result<BufferId> {
+        let (old, path) = self.revert_source(buffer_id)?;
+        let new = self
+            .reload_file_state(&path)?
+            .buffer
+            .get_all_text()
+            .unwrap_or_default();
+        let name = self
+            .buffer_metadata
+            .get(&buffer_id)
+            .map(|m| m.display_name.clone())
+            .unwrap_or_else(|| path.display().to_string());
+
+        let old_starts = line_starts(&old);
+        let new_starts = line_starts(&new);
+        let matches = matching_lines(&old, &new);
+        let hunks = diff_hunks(&old, &old_starts, &new_starts, &matches);
+
+        let mut text = format!("{}\n\n", t!("revert.preview_header", name = name));
+        if hunks.is_empty() {
+            text.push_str(&format!("{}\n", t!("revert.preview_unchanged")));
+        }
+        let line_text = |source: &[u8], starts: &[usize], line: usize| {
+            let end = (starts[line + 1] - 1).min(source.len());
+            String::from_utf8_lossy(&source[starts[line]..end]).into_owned()
+        };
+        for hunk in &hunks {
+            text.push_str(&format!(
+                "{}\n",
+                t!("revert.preview_hunk", line = hunk.old_lines.start + 1)
+            ));
+            for line in hunk.old_lines.clone() {
+                text.push_str(&format!("- {}\n", line_text(&old, &old_starts, line)));
+            }
+            for line in hunk.new_lines.clone() {
+                text.push_str(&format!("+ {}\n", line_text(&new, &new_starts, line)));
+            }
+        }
+
+        let preview = match preview.filter(|id| self.buffers.contains_key(id)) {
+            Some(id) => id,
+            None => self.create_virtual_buffer(
+                PREVIEW_BUFFER_NAME.to_string(),
+                "special".to_string(),
+                true,
+            ),
+        };
+        self.set_virtual_buffer_content(preview, vec![TextPropertyEntry::text(text)])
+            .map_err(std::io::Error::other)?;
+        self.set_active_buffer(preview);
+        Ok(preview)
+    }
+
+    /// Current text and file path of a buffer to revert
+    fn revert_source(&self, buffer_id: BufferId) -> std::io::Result<(Vec<u8>, std::path::PathBuf)> {
+        let state = self
+            .buffers
+            .get(&buffer_id)
+            .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::NotFound, "Buffer not found"))?;
+        let path = state
+            .buffer
+            .file_path()
+            .map(Path::to_path_buf)
+            .ok_or_else(|| std::io::Error::other(t!("status.no_file_to_revert").to_string()))?;
+        let text = state
+            .buffer
+            .get_all_text()
+            .ok_or_else(|| std::io::Error::other(t!("status.no_file_to_revert").to_string()))?;
+        Ok((text, path))
+    }
+
+    /// Replace the changed lines of a buffer with the file on disk
+    fn apply_revert_to_saved(&mut self, buffer_id: BufferId) {
+        if self.active_buffer() != buffer_id {
+            if !self.buffers.contains_key(&buffer_id) {
+                return;
+            }
+            self.set_active_buffer(buffer_id);
+        }
+
+        // Large files don't keep their text in memory; swap the buffer instead
+        if self.active_state().buffer.is_large_file() {
+            if let Err(e) = self.revert_file() {
+                self.set_status_message(
+                    t!("error.failed_to_revert", error = e.to_string()).to_string(),
+                );
+            }
+            return;
+        }
+
+        let missing = self
+            .active_state()
+            .buffer
+            .file_path()
+            .filter(|path| !path.exists())
+            .map(Path::to_path_buf);
+        if let Some(path) = missing {
+            self.set_status_message(
+                t!("status.file_not_exists", path = path.display().to_string()).to_string(),
+            );
+            return;
+        }
+
+        let description = t!("cmd.revert_buffer_to_saved").to_string();
+        match self.replace_with_saved(buffer_id, description) {
+            Ok(()) => self.set_status_message(t!("status.reverted_to_saved").to_string()),
+            Err(e) => self.set_status_message(
+                t!("error.failed_to_revert", error = e.to_string()).to_string(),
+            ),
+        }
+    }
+
+    /// Replace the changed lines of the active buffer with its file on disk,
+    /// as an undoable edit named `description`, and mark it saved
+    pub(super) fn replace_with_saved(
+        &mut self,
+        buffer_id: BufferId,
+        description: String,
+    ) -> std::io::Result<()> {
+        let (old, path) = self.revert_source(buffer_id)?;
+        if !path.exists() {
+            return Err(std::io::Error::other(
+                t!("status.file_not_exists", path = path.display().to_string()).to_string(),
+            ));
+        }
+        let reloaded = self.reload_file_state(&path)?;
+        let Some(new) = reloaded.buffer.get_all_text() else {
+            return self.revert_file().map(|_| ());
+        };
+
+        self.replace_changed_lines(&old, &new, description);
+
+        // Positions of overlays outside stable namespaces refer to the old text
+        let stable = &self.revert_stable_namespaces;
+        let Some(state) = self.buffers.get_mut(&buffer_id) else {
+            return Ok(());
+        };
+        let stale: Vec<_> = state
+            .overlays
+            .all()
+            .iter()
+            .filter(|o| o.namespace.as_ref().is_none_or(|ns| !stable.contains(ns)))
+            .map(|o| o.handle.clone())
+            .collect();
+        for handle in &stale {
+            state
+                .overlays
+                .remove_by_handle(handle, &mut state.marker_list);
+        }
+
+        state.buffer.set_encoding(reloaded.buffer.encoding());
+        state.buffer.set_line_ending(reloaded.buffer.line_ending());
+        state.buffer.mark_saved_snapshot();
+        self.active_event_log_mut().mark_saved();
+
+        self.seen_byte_ranges.remove(&buffer_id);
+        if let Ok(mtime) = std::fs::metadata(&path).and_then(|m| m.modified()) {
+            self.file_mod_times.insert(path.clone(), mtime);
+        }
+        self.notify_lsp_file_changed(&path);
+
+        self.plugin_manager.run_hook(
+            "buffer_reverted",
+            HookArgs::BufferReverted { buffer_id, path },
+        );
+        Ok(())
+    }
+
+    /// Turn the active buffer's text `old` into `new` by replacing only the
+    /// lines that differ, as one undoable edit named `description` in undo
+    /// messages
+    ///
+    /// Cursors, markers and scroll positions follow the lines as described
+    /// in the module docs. Returns false if nothing differed.
+    pub(super) fn replace_changed_lines(
+        &mut self,
+        old: &[u8],
+        new: &[u8],
+        description: String,
+    ) -> bool {
+        let buffer_id = self.active_buffer();
+        let old_starts = line_starts(old);
+        let new_starts = line_starts(new);
+        let matches = matching_lines(old, new);
+        let hunks = diff_hunks(old, &old_starts, &new_starts, &matches);
+        if hunks.is_empty() {
+            return false;
+        }
+        let offsets = OffsetMap {
+            new,
+            old_starts: &old_starts,
+            new_starts: &new_starts,
+            matches: &matches,
+        };
+
+        // Scroll positions, mapped before the text changes
+        let splits = self.split_manager.splits_for_buffer(buffer_id);
+        let top_bytes: Vec<_> = splits
+            .iter()
+            .filter_map(|split| {
+                self.split_view_states
+                    .get(split)
+                    .map(|vs| (*split, offsets.map(vs.viewport.top_byte)))
+            })
+            .collect();
+
+        let state = self.active_state();
+        let cursor_id = state.cursors.primary_id();
+        let mut events = Vec::with_capacity(hunks.len() * 2 + state.cursors.count());
+        for hunk in &hunks {
+            if !hunk.old_bytes.is_empty() {
+                events.push(Event::Delete {
+                    range: hunk.old_bytes.clone(),
+                    deleted_text: String::from_utf8_lossy(&old[hunk.old_bytes.clone()])
+                        .into_owned(),
+                    cursor_id,
+                });
+            }
+            if !hunk.new_bytes.is_empty() {
+                events.push(Event::Insert {
+                    position: hunk.old_bytes.start,
+                    text: String::from_utf8_lossy(&new[hunk.new_bytes.clone()]).into_owned(),
+                    cursor_id,
+                });
+            }
+        }
+        for (id, cursor) in state.cursors.iter() {
+            events.push(Event::MoveCursor {
+                cursor_id: id,
+                old_position: cursor.position,
+                new_position: offsets.map(cursor.position),
+                old_anchor: cursor.anchor,
+                new_anchor: None,
+                old_sticky_column: cursor.sticky_column,
+                new_sticky_column: 0,
+            });
+        }
+
+        // The bulk edit moves markers and margins along with the text
+        if let Some(bulk_edit) = self.apply_events_as_bulk_edit(events, description.clone()) {
+            self.active_event_log_mut()
+                .append_labeled(bulk_edit, description);
+        }
+
+        for (split, top_byte) in top_bytes {
+            if let Some(view_state) = self.split_view_states.get_mut(&split) {
+                view_state.viewport.top_byte = top_byte;
+            }
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Apply hunks to `old` the way the bulk edit does
+    fn apply(old: &str, new: &str) -> String {
+        let (old_b, new_b) = (old.as_bytes(), new.as_bytes());
+        let old_starts = line_starts(old_b);
+        let new_starts = line_starts(new_b);
+        let matches = matching_lines(old_b, new_b);
+        let mut text = old_b.to_vec();
+        for hunk in diff_hunks(old_b, &old_starts, &new_starts, &matches)
+            .iter()
+            .rev()
+        {
+            text.splice(
+                hunk.old_bytes.clone(),
+                new_b[hunk.new_bytes.clone()].to_vec(),
+            );
+        }
+        String::from_utf8(text).unwrap()
+    }
+
+    fn map(old: &str, new: &str, offset: usize) -> usize {
+        let old_starts = line_starts(old.as_bytes());
+        let new_starts = line_starts(new.as_bytes());
+        let matches = matching_lines(old.as_bytes(), new.as_bytes());
+        let offsets = OffsetMap {
+            new: new.as_bytes(),
+            old_starts: &old_starts,
+            new_starts: &new_starts,
+            matches: &matches,
+        };
+        offsets.map(offset)
+    }
+
+    #[test]
+    fn test_line_starts_include_virtual_newline() {
+        assert_eq!(line_starts(b"ab\ncd"), vec![0, 3, 6]);
+        assert_eq!(line_starts(b"ab\n"), vec![0, 3, 4]);
+        assert_eq!(line_starts(b""), vec![0, 1]);
+    }
+
+    #[test]
+    fn test_hunks_reproduce_new_text() {
+        let cases = [
+            ("a\nb\nc\n", "a\nx\nc\n"),
+            ("a\nb\nc\n", "a\nc\n"),
+            ("a\nc\n", "a\nb\nc\n"),
+            ("a\nb", "a\nb\nc"),
+            ("a\nb\nc", "a\nb"),
+            ("a\nb", "a\nb\n"),
+            ("a\nb\n", "a\nb"),
+            ("", "one\ntwo\n"),
+            ("one\ntwo\n", ""),
+            ("a\nb\nc\nd\ne\n", "z\nb\nc\ny\ne\nf"),
+            ("same\n", "same\n"),
+        ];
+        for (old, new) in cases {
+            assert_eq!(apply(old, new), new, "reverting {:?} to {:?}", old, new);
+        }
+    }
+
+    #[test]
+    fn test_hunks_only_cover_changed_lines() {
+        let old = b"a\nb\nc\nd\n";
+        let new = b"a\nB\nc\nd\n";
+        let old_starts = line_starts(old);
+        let new_starts = line_starts(new);
+        let matches = matching_lines(old, new);
+        assert_eq!(
+            diff_hunks(old, &old_starts, &new_starts, &matches),
+            vec![Hunk {
+                old_lines: 1..2,
+                new_lines: 1..2,
+                old_bytes: 2..4,
+                new_bytes: 2..4,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_offset_map_follows_unchanged_lines() {
+        // Cursor on "target" (line 2, column 3) after a line is added above it
+        let old = "one\ntwo\ntarget\n";
+        let new = "zero\none\ntwo\ntarget\n";
+        assert_eq!(map(old, new, 11), 16);
+
+        // A changed line keeps its line number and clamps the column
+        let old = "one\nlonger line\nthree\n";
+        let new = "one\nshort\nthree\n";
+        assert_eq!(map(old, new, 14), 9);
+
+        // A changed line below added lines moves down with them
+        let old = "one\ntwo!\nthree\n";
+        let new = "zero\none\ntwo\nthree\n";
+        assert_eq!(map(old, new, 8), 12);
+
+        // Past the end of a shorter file
+        assert_eq!(map("a\nb\nc\nd\n", "a\n", 6), 2);
+    }
+
+    #[test]
+    fn test_offset_map_snaps_to_char_boundary() {
+        // Column 2 falls inside "é" on the changed line
+        let old = "abc\n";
+        let new = "aé\n";
+        assert_eq!(map(old, new, 2), 1);
+    }
+}
@@ -0,0 +1,749 @@
+//! Save actions
+//!
+//! A language's `save_actions` are steps run on the buffer before it is
+//! saved, in the configured order, each on the text the one before left:
+//!
+//! - `trim_trailing_whitespace` and `ensure_final_newline`
+//! - `format`, the language's formatter (see [`super::formatting`])
+//! - `lsp:<kind>`, the first code action of that kind the language server
+//!   offers for the whole file, e.g. `lsp:source.organizeImports`
+//! - `plugin:<name>`, the handler a plugin registered for the
+//!   `save_action:<name>` hook; it edits the buffer (e.g. with `applyEdits`)
+//!   and then calls `finishSaveAction`
+//!
+//! Steps that wait on a formatter, server or plugin are finished by
+//! [`Editor::poll_save_actions`]; `lsp:` and `plugin:` steps fail after the
+//! language's `save_actions_timeout_ms`. Editing the buffer or switching to
+//! another meanwhile cancels the save.
+//!
+//! Together the steps' edits are one undo step. A step that fails is
+//! reported, and `save_actions_on_failure` decides whether the remaining
+//! steps run and the file is saved. Like format-on-save, only saves the user
+//! asks for run save actions: recovery writes, retries and "save and close"
+//! don't, and neither do large files.
+
+use std::collections::VecDeque;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use rust_i18n::t;
+
+use super::formatting::QUICK_FORMAT_TIMEOUT;
+use super::lsp_rename::workspace_edit_files;
+use super::Editor;
+use crate::config::SaveActionFailure;
+use crate::model::event::{BufferId, CursorId, Event};
+use crate::model::piece_tree::PieceTree;
+use crate::services::file_provider::is_remote_path;
+use crate::services::formatter::{FormatCommand, FormatError, FormatInput, FormatJob};
+use crate::services::lsp::manager::detect_language;
+use crate::services::plugins::hooks::{save_action_hook_name, HookArgs};
+use crate::view::notifications::NotificationSeverity;
+
+/// One step of a language's `save_actions`
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum SaveAction {
+    TrimTrailingWhitespace,
+    EnsureFinalNewline,
+    /// The language's formatter
+    Format,
+    /// The first code action of this kind the language server offers
+    Lsp(String),
+    /// The handler a plugin registered for `save_action:<name>`
+    Plugin(String),
+}
+
+impl SaveAction {
+    fn parse(spec: &str) -> Option<Self> {
+        match spec {
+            "trim_trailing_whitespace" => Some(Self::TrimTrailingWhitespace),
+            "ensure_final_newline" => Some(Self::EnsureFinalNewline),
+            "format" => Some(Self::Format),
+            _ => {
+                if let Some(kind) = spec.strip_prefix("lsp:") {
+                    (!kind.is_empty()).then(|| Self::Lsp(kind.to_string()))
+                } else {
+                    let name = spec.strip_prefix("plugin:")?;
+                    (!name.is_empty()).then(|| Self::Plugin(name.to_string()))
+                }
+            }
+        }
+    }
+}
+
+/// What a running step waits for
+enum Waiting {
+    Format {
+        job: FormatJob,
+        /// Copy of the buffer a file-mode formatter works on
+        copy: Option<PathBuf>,
+    },
+    Lsp {
+        request_id: u64,
+        deadline: Instant,
+    },
+    Plugin {
+        request_id: u32,
+        deadline: Instant,
+    },
+}
+
+/// Save actions running on the active buffer
+pub(crate) struct SaveActionRun {
+    buffer_id: BufferId,
+    path: PathBuf,
+    /// Steps not started yet, as configured
+    steps: VecDeque<String>,
+    /// The step waiting for its result
+    waiting: Option<(String, Waiting)>,
+    /// Save the buffer once the steps are done
+    save: bool,
+    on_failure: SaveActionFailure,
+    timeout: Duration,
+    /// Event log position before the first step
+    start_index: usize,
+    /// Event log position after the last step, to notice other edits
+    log_index: usize,
+    /// Text and cursors before the first step, for the combined undo step
+    old_tree: Arc<PieceTree>,
+    old_cursors: Vec<(CursorId, usize, Option<usize>)>,
+    /// Number of steps that succeeded
+    succeeded: usize,
+    /// Messages of the steps that failed
+    errors: Vec<String>,
+    /// Progress entry shown while a step waits
+    progress_id: Option<u32>,
+}
+
+impl Editor {
+    /// Whether saving a buffer runs its language's save actions
+    pub(super) fn has_save_actions(&self, buffer_id: BufferId) -> bool {
+        self.save_actions_language(buffer_id).is_some()
+    }
+
+    /// Whether save actions are running on a buffer
+    pub fn is_running_save_actions(&self, buffer_id: BufferId) -> bool {
+        self.save_actions
+            .as_ref()
+            .is_some_and(|run| run.buffer_id == buffer_id)
+    }
+
+    /// The language of a buffer, if it has save actions
+    fn save_actions_language(&self, buffer_id: BufferId) -> Option<String> {
+        let path = self.buffers.get(&buffer_id)?.buffer.file_path()?;
+        let language = detect_language(path, &self.config.languages)?;
+        self.config
+            .languages
+            .get(&language)
+            .is_some_and(|config| !config.save_actions.is_empty())
+            .then_some(language)
+    }
+
+    /// Run the active buffer's save actions, then save it if `save` is set
+    ///
+    /// Without save actions, or for a large file, saving goes ahead as is.
+    pub fn run_save_actions(&mut self, save: bool) -> io::Result<()> {
+        let buffer_id = self.active_buffer();
+        if self.save_actions.is_some() {
+            self.set_status_message(t!("save_actions.in_progress").to_string());
+            return Ok(());
+        }
+        let Some(language) = self.save_actions_language(buffer_id) else {
+            if save {
+                return self.save();
+            }
+            self.set_status_message(t!("save_actions.none").to_string());
+            return Ok(());
+        };
+        let Some(state) = self.buffers.get(&buffer_id) else {
+            return Ok(());
+        };
+        if state.buffer.is_large_file() {
+            tracing::info!("Skipping save actions for a large file");
+            if save {
+                return self.save();
+            }
+            self.set_status_message(t!("save_actions.large_file").to_string());
+            return Ok(());
+        }
+
+        let path = state
+            .buffer
+            .file_path()
+            .map(Path::to_path_buf)
+            .unwrap_or_default();
+        let config = &self.config.languages[&language];
+        let log_index = self.buffer_log_index(buffer_id);
+        self.save_actions = Some(SaveActionRun {
+            buffer_id,
+            path,
+            steps: config.save_actions.iter().cloned().collect(),
+            waiting: None,
+            save,
+            on_failure: config.save_actions_on_failure,
+            timeout: Duration::from_millis(config.save_actions_timeout_ms),
+            start_index: log_index,
+            log_index,
+            old_tree: state.buffer.snapshot_piece_tree(),
+            old_cursors: state
+                .cursors
+                .iter()
+                .map(|(id, cursor)| (id, cursor.position, cursor.anchor))
+                .collect(),
+            succeeded: 0,
+            errors: Vec::new(),
+            progress_id: None,
+        });
+        self.continue_save_actions();
+        Ok(())
+    }
+
+    fn buffer_log_index(&self, buffer_id: BufferId) -> usize {
+        self.event_logs
+            .get(&buffer_id)
+            .map_or(0, |log| log.current_index())
+    }
+
+    /// Start steps until one has to wait, or finish when none are left
+    fn continue_save_actions(&mut self) {
+        loop {
+            let Some(run) = self.save_actions.as_mut() else {
+                return;
+            };
+            if run.waiting.is_some() {
+                return;
+            }
+            let Some(spec) = run.steps.pop_front() else {
+                self.finish_save_actions();
+                return;
+            };
+            let result = match SaveAction::parse(&spec) {
+                Some(action) => self.start_save_action(&spec, action),
+                None => Err(t!("save_actions.unknown").to_string()),
+            };
+            let waiting = self
+                .save_actions
+                .as_ref()
+                .is_some_and(|run| run.waiting.is_some());
+            if waiting || !self.record_save_action(&spec, result) {
+                return;
+            }
+        }
+    }
+
+    /// Run a step, or start it and leave it waiting
+    fn start_save_action(&mut self, spec: &str, action: SaveAction) -> Result<(), String> {
+        let Some(run) = self.save_actions.as_ref() else {
+            return Ok(());
+        };
+        let buffer_id = run.buffer_id;
+        let timeout = run.timeout;
+        let path = run.path.clone();
+        match action {
+            SaveAction::TrimTrailingWhitespace => {
+                let text = self.text_to_format(buffer_id)?;
+                self.apply_save_action_text(&text, &trim_trailing_whitespace(&text));
+                Ok(())
+            }
+            SaveAction::EnsureFinalNewline => {
+                let text = self.text_to_format(buffer_id)?;
+                let line_ending = self.active_state().buffer.line_ending().as_str();
+                self.apply_save_action_text(&text, &ensure_final_newline(&text, line_ending));
+                Ok(())
+            }
+            SaveAction::Format => {
+                let (language, formatter, path) = self.buffer_formatter(buffer_id)?;
+                self.authorize_formatter(&language, &formatter)?;
+                if !formatter.stdin && is_remote_path(&path) {
+                    return Err(t!("format.remote_file_mode").to_string());
+                }
+                let (input, copy) = if formatter.stdin {
+                    (FormatInput::Stdin(self.text_to_format(buffer_id)?), None)
+                } else {
+                    let copy = self
+                        .write_format_copy(buffer_id, &path)
+                        .map_err(|e| e.to_string())?;
+                    (FormatInput::File(copy.clone()), Some(copy))
+                };
+                let file = copy.clone().unwrap_or_else(|| path.clone());
+                let working_dir = std::env::current_dir()
+                    .unwrap_or_else(|_| file.parent().unwrap_or(Path::new(".")).to_path_buf());
+                let command =
+                    FormatCommand::new(&formatter.command, &formatter.args, &file, &working_dir)
+                        .with_timeout(Duration::from_millis(formatter.timeout_ms));
+                let mut job = FormatJob::start(&self.worker_pool, command, input, &file);
+                match job.wait(QUICK_FORMAT_TIMEOUT) {
+                    Some(result) => {
+                        if let Some(copy) = &copy {
+                            let _ = std::fs::remove_file(copy);
+                        }
+                        self.apply_formatted_save_action(result)
+                    }
+                    None => {
+                        self.wait_for_save_action(spec, Waiting::Format { job, copy });
+                        Ok(())
+                    }
+                }
+            }
+            SaveAction::Lsp(kind) => {
+                let request_id = self.next_lsp_request_id;
+                let encoding = self.lsp_position_encoding(buffer_id);
+                let state = self.active_state();
+                let (end_line, end_char) = state
+                    .buffer
+                    .position_to_lsp_position(state.buffer.len(), encoding);
+                // Of several servers, the one offering the kind is asked
+                let server = self
+                    .buffer_metadata
+                    .get(&buffer_id)
+                    .and_then(|metadata| metadata.file_path())
+                    .and_then(|path| detect_language(path, &self.config.languages))
+                    .and_then(|language| self.code_action_server(&language, &kind));
+                let only = Some(vec![lsp_types::CodeActionKind::from(kind)]);
+                let sent = server
+                    .and_then(|server| {
+                        self.with_lsp_server_for_buffer(
+                            buffer_id,
+                            &server,
+                            |handle, uri, _language| {
+                                handle
+                                    .code_actions(
+                                        request_id,
+                                        uri.clone(),
+                                        0,
+                                        0,
+                                        end_line as u32,
+                                        end_char as u32,
+                                        Vec::new(),
+                                        only,
+                                    )
+                                    .is_ok()
+                            },
+                        )
+                    })
+                    .unwrap_or(false);
+                if !sent {
+                    return Err(t!("save_actions.no_language_server").to_string());
+                }
+                self.next_lsp_request_id += 1;
+                let deadline = self.time_source.now() + timeout;
+                self.wait_for_save_action(
+                    spec,
+                    Waiting::Lsp {
+                        request_id,
+                        deadline,
+                    },
+                );
+                Ok(())
+            }
+            SaveAction::Plugin(name) => {
+                let hook = save_action_hook_name(&name);
+                if !self.plugin_manager.has_hook_handlers(&hook) {
+                    return Err(t!("save_actions.no_plugin").to_string());
+                }
+                let request_id = self.next_save_action_request;
+                self.next_save_action_request += 1;
+                self.plugin_manager.run_hook(
+                    &hook,
+                    HookArgs::SaveAction {
+                        request_id,
+                        buffer_id,
+                        path,
+                        action: name,
+                    },
+                );
+                let deadline = self.time_source.now() + timeout;
+                self.wait_for_save_action(
+                    spec,
+                    Waiting::Plugin {
+                        request_id,
+                        deadline,
+                    },
+                );
+                Ok(())
+            }
+        }
+    }
+
+    /// Leave a step waiting for its result, showing its progress
+    fn wait_for_save_action(&mut self, spec: &str, waiting: Waiting) {
+        let progress_id =
+            self.start_progress(t!("save_actions.running", action = spec).to_string());
+        if let Some(run) = self.save_actions.as_mut() {
+            run.waiting = Some((spec.to_string(), waiting));
+            run.progress_id = Some(progress_id);
+        }
+    }
+
+    /// Replace the buffer's text with a step's result
+    fn apply_save_action_text(&mut self, old: &str, new: &str) {
+        if old != new {
+            self.replace_changed_lines(
+                old.as_bytes(),
+                new.as_bytes(),
+                t!("cmd.run_save_actions").to_string(),
+            );
+        }
+    }
+
+    fn apply_formatted_save_action(
+        &mut self,
+        result: Result<String, FormatError>,
+    ) -> Result<(), String> {
+        let formatted = result.map_err(|e| e.to_string())?;
+        let buffer_id = self.active_buffer();
+        let text = self.text_to_format(buffer_id)?;
+        self.apply_save_action_text(&text, &formatted);
+        Ok(())
+    }
+
+    /// Record how a waiting step ended and go on with the next one
+    fn end_save_action(&mut self, spec: &str, result: Result<(), String>) {
+        if self.record_save_action(spec, result) {
+            self.continue_save_actions();
+        }
+    }
+
+    /// Record how a step ended; returns false if that ended the save actions
+    fn record_save_action(&mut self, spec: &str, result: Result<(), String>) -> bool {
+        let Some(buffer_id) = self.save_actions.as_ref().map(|run| run.buffer_id) else {
+            return false;
+        };
+        let log_index = self.buffer_log_index(buffer_id);
+        let Some(run) = self.save_actions.as_mut() else {
+            return false;
+        };
+        run.log_index = log_index;
+        if let Some(progress_id) = run.progress_id.take() {
+            self.finish_progress(progress_id);
+        }
+        let Some(run) = self.save_actions.as_mut() else {
+            return false;
+        };
+        match result {
+            Ok(()) => run.succeeded += 1,
+            Err(error) => {
+                tracing::warn!("Save action '{}' failed: {}", spec, error);
+                let message = t!("save_actions.failed", action = spec, error = error).to_string();
+                run.errors.push(message.clone());
+                let abort = run.on_failure == SaveActionFailure::Abort;
+                self.notify(NotificationSeverity::Error, message);
+                if abort {
+                    self.finish_save_actions();
+                    return false;
+                }
+            }
+        }
+        true
+    }
+
+    /// Whether the buffer changed under a waiting step, or was switched away from
+    fn save_actions_stale(&self, allow_edits: bool) -> bool {
+        self.save_actions.as_ref().is_some_and(|run| {
+            run.buffer_id != self.active_buffer()
+                || (!allow_edits && self.buffer_log_index(run.buffer_id) != run.log_index)
+        })
+    }
+
+    /// Finish steps whose result arrived or whose time ran out
+    ///
+    /// Returns true if anything visible changed.
+    pub(super) fn poll_save_actions(&mut self) -> bool {
+        let now = self.time_source.now();
+        let Some((_, waiting)) = self
+            .save_actions
+            .as_mut()
+            .and_then(|run| run.waiting.as_mut())
+        else {
+            return false;
+        };
+        let formatted = match waiting {
+            Waiting::Format { job, .. } => match job.poll() {
+                Some(result) => Some(result),
+                None => return false,
+            },
+            Waiting::Lsp { deadline, .. } | Waiting::Plugin { deadline, .. } => {
+                if now < *deadline {
+                    return false;
+                }
+                None
+            }
+        };
+        let Some((spec, waiting)) = self
+            .save_actions
+            .as_mut()
+            .and_then(|run| run.waiting.take())
+        else {
+            return false;
+        };
+
+        let result = match (waiting, formatted) {
+            (Waiting::Format { copy, .. }, Some(result)) => {
+                if let Some(copy) = &copy {
+                    let _ = std::fs::remove_file(copy);
+                }
+                if self.save_actions_stale(false) {
+                    self.cancel_save_actions(true);
+                    return true;
+                }
+                self.apply_formatted_save_action(result)
+            }
+            _ => {
+                let timeout = self
+                    .save_actions
+                    .as_ref()
+                    .map_or(0, |run| run.timeout.as_millis());
+                Err(t!("save_actions.timed_out", ms = timeout).to_string())
+            }
+        };
+        self.end_save_action(&spec, result);
+        true
+    }
+
+    /// Whether an LSP response is for a waiting `lsp:` step
+    pub(super) fn is_save_action_request(&self, request_id: u64) -> bool {
+        matches!(
+            self.save_actions.as_ref().and_then(|run| run.waiting.as_ref()),
+            Some((_, Waiting::Lsp { request_id: id, .. })) if *id == request_id
+        )
+    }
+
+    /// Apply the first code action with edits the server offered for an
+    /// `lsp:` step
+    pub(super) fn handle_save_action_code_actions(
+        &mut self,
+        request_id: u64,
+        actions: Vec<lsp_types::CodeActionOrCommand>,
+    ) {
+        if !self.is_save_action_request(request_id) {
+            return;
+        }
+        let Some((spec, _)) = self
+            .save_actions
+            .as_mut()
+            .and_then(|run| run.waiting.take())
+        else {
+            return;
+        };
+        if self.save_actions_stale(false) {
+            self.cancel_save_actions(true);
+            return;
+        }
+        let edit = actions.into_iter().find_map(|action| match action {
+            lsp_types::CodeActionOrCommand::CodeAction(action) => action.edit,
+            lsp_types::CodeActionOrCommand::Command(_) => None,
+        });
+        let result = match edit {
+            Some(edit) => self.apply_save_action_workspace_edit(edit),
+            None => Ok(()),
+        };
+        self.end_save_action(&spec, result);
+    }
+
+    /// Apply the edits of a workspace edit that are for the buffer
+    fn apply_save_action_workspace_edit(
+        &mut self,
+        edit: lsp_types::WorkspaceEdit,
+    ) -> Result<(), String> {
+        let Some(run) = self.save_actions.as_ref() else {
+            return Ok(());
+        };
+        let (buffer_id, path) = (run.buffer_id, run.path.clone());
+        let edits = workspace_edit_files(edit)?
+            .into_iter()
+            .filter(|(file, _)| *file == path)
+            .flat_map(|(_, edits)| edits)
+            .collect();
+        self.apply_lsp_text_edits(buffer_id, edits)
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+    }
+
+    /// End a waiting `plugin:` step at its plugin's request
+    pub(super) fn finish_plugin_save_action(&mut self, request_id: u32, error: Option<String>) {
+        let waiting_for_it = matches!(
+            self.save_actions.as_ref().and_then(|run| run.waiting.as_ref()),
+            Some((_, Waiting::Plugin { request_id: id, .. })) if *id == request_id
+        );
+        if !waiting_for_it {
+            tracing::debug!(
+                "Ignoring finished save action {}: not waiting for it",
+                request_id
+            );
+            return;
+        }
+        let Some((spec, _)) = self
+            .save_actions
+            .as_mut()
+            .and_then(|run| run.waiting.take())
+        else {
+            return;
+        };
+        // The plugin's own edits are expected
+        if self.save_actions_stale(true) {
+            self.cancel_save_actions(true);
+            return;
+        }
+        self.end_save_action(&spec, error.map_or(Ok(()), Err));
+    }
+
+    /// Stop the save actions of a buffer that is being closed
+    pub(super) fn on_save_actions_buffer_closed(&mut self, buffer_id: BufferId) {
+        if self.is_running_save_actions(buffer_id) {
+            self.cancel_save_actions(false);
+        }
+    }
+
+    /// Stop running save actions without saving, keeping the steps' edits
+    fn cancel_save_actions(&mut self, report: bool) {
+        let Some(run) = self.save_actions.take() else {
+            return;
+        };
+        if let Some(progress_id) = run.progress_id {
+            self.finish_progress(progress_id);
+        }
+        if let Some((_, Waiting::Format { job, copy })) = run.waiting {
+            job.cancel();
+            if let Some(copy) = &copy {
+                let _ = std::fs::remove_file(copy);
+            }
+        }
+        if report {
+            self.set_status_message(t!("save_actions.cancelled").to_string());
+        }
+    }
+
+    /// Combine the steps' edits into one undo step, then save if asked to
+    fn finish_save_actions(&mut self) {
+        let Some(run) = self.save_actions.take() else {
+            return;
+        };
+        if let Some(progress_id) = run.progress_id {
+            self.finish_progress(progress_id);
+        }
+        self.squash_save_action_edits(&run);
+
+        let aborted = !run.errors.is_empty() && run.on_failure == SaveActionFailure::Abort;
+        let last_error = run.errors.last().cloned().unwrap_or_default();
+        if !run.save {
+            let message = if run.errors.is_empty() {
+                t!("save_actions.ran", count = run.succeeded).to_string()
+            } else {
+                last_error
+            };
+            self.set_status_message(message);
+            return;
+        }
+        if aborted {
+            self.set_status_message(
+                t!("save_actions.failed_not_saved", error = last_error).to_string(),
+            );
+            return;
+        }
+        match self.save_buffer(run.buffer_id) {
+            Ok(()) if !run.errors.is_empty() => self.set_status_message(
+                t!("save_actions.failed_saved", error = last_error).to_string(),
+            ),
+            Ok(()) => {}
+            Err(e) => self.report_failed_save(run.buffer_id, &run.path, e),
+        }
+    }
+
+    /// Replace the log entries of the steps' edits with one entry going from
+    /// the text before the first step to the text now
+    fn squash_save_action_edits(&mut self, run: &SaveActionRun) {
+        let Some(state) = self.buffers.get(&run.buffer_id) else {
+            return;
+        };
+        let Some(log) = self.event_logs.get_mut(&run.buffer_id) else {
+            return;
+        };
+        let current = log.current_index();
+        if current <= run.start_index
+            || !log
+                .range(run.start_index..current)
+                .iter()
+                .any(|entry| entry.event.modifies_buffer())
+        {
+            return;
+        }
+        let event = Event::BulkEdit {
+            old_tree: Some(Arc::clone(&run.old_tree)),
+            new_tree: Some(state.buffer.snapshot_piece_tree()),
+            old_cursors: run.old_cursors.clone(),
+            new_cursors: state
+                .cursors
+                .iter()
+                .map(|(id, cursor)| (id, cursor.position, cursor.anchor))
+                .collect(),
+            description: t!("cmd.run_save_actions").to_string(),
+        };
+        log.squash_from(
+            run.start_index,
+            event,
+            t!("cmd.run_save_actions").to_string(),
+        );
+    }
+}
+
+/// `text` without spaces and tabs at the ends of its lines
+fn trim_trailing_whitespace(text: &str) -> String {
+    let mut trimmed = String::with_capacity(text.len());
+    for line in text.split_inclusive('\n') {
+        let content = line.trim_end_matches(['\n', '\r']);
+        trimmed.push_str(content.trim_end_matches([' ', '\t']));
+        trimmed.push_str(&line[content.len()..]);
+    }
+    trimmed
+}
+
+/// `text` ending with a line ending, unless it is empty
+fn ensure_final_newline(text: &str, line_ending: &str) -> String {
+    if text.is_empty() || text.ends_with(['\n', '\r']) {
+        text.to_string()
+    } else {
+        format!("{}{}", text, line_ending)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_save_actions() {
+        assert_eq!(
+            SaveAction::parse("trim_trailing_whitespace"),
+            Some(SaveAction::TrimTrailingWhitespace)
+        );
+        assert_eq!(SaveAction::parse("format"), Some(SaveAction::Format));
+        assert_eq!(
+            SaveAction::parse("lsp:source.organizeImports"),
+            Some(SaveAction::Lsp("source.organizeImports".to_string()))
+        );
+        assert_eq!(
+            SaveAction::parse("plugin:headers.fix"),
+            Some(SaveAction::Plugin("headers.fix".to_string()))
+        );
+        assert_eq!(SaveAction::parse("lsp:"), None);
+        assert_eq!(SaveAction::parse("formatter"), None);
+    }
+
+    #[test]
+    fn test_trim_trailing_whitespace_keeps_line_endings() {
+        assert_eq!(
+            trim_trailing_whitespace("a  \r\n\tb\t\n  \nc "),
+            "a\r\n\tb\n\nc"
+        );
+    }
+
+    #[test]
+    fn test_ensure_final_newline() {
+        assert_eq!(ensure_final_newline("a\nb", "\n"), "a\nb\n");
+        assert_eq!(ensure_final_newline("a\r\nb", "\r\n"), "a\r\nb\r\n");
+        assert_eq!(ensure_final_newline("a\n", "\n"), "a\n");
+        assert_eq!(ensure_final_newline("", "\n"), "");
+    }
+}
@@ -0,0 +1,146 @@
+//! Case matching of in-buffer search and replace
+//!
+//! With `search.smart_case` off, the case-sensitive toggle decides, as it
+//! always has. With it on, a query matches case-sensitively only if it has an
+//! uppercase letter (see [`crate::primitives::case_matching`]); the toggle
+//! then flips the case matching of the current search without touching the
+//! config, and the next search starts from the query again.
+
+use std::ops::Range;
+
+use regex::bytes::Regex;
+use rust_i18n::t;
+
+use super::Editor;
+use crate::model::buffer::Buffer;
+use crate::primitives::case_matching::{preserve_case, CaseMatching};
+use crate::view::prompt::PromptType;
+
+/// How replace finds the search text, and what it puts in its place
+#[derive(Debug, Clone)]
+pub(super) struct ReplaceMatcher {
+    search: String,
+    /// Matches `search` in any case; `None` matches it as typed
+    ignore_case: Option<Regex>,
+    /// Replace in the case of each match (`search.preserve_case`)
+    preserve_case: bool,
+}
+
+impl ReplaceMatcher {
+    /// The first match in `range` of `buffer`
+    pub fn find(&self, buffer: &Buffer, range: Range<usize>) -> Option<Range<usize>> {
+        match &self.ignore_case {
+            Some(regex) => buffer.find_regex_match_in_range(regex, range),
+            None => buffer
+                .find_next_in_range(&self.search, range.start, Some(range))
+                .map(|start| start..start + self.search.len()),
+        }
+    }
+
+    /// Whether matches can differ from the search text
+    pub fn ignores_case(&self) -> bool {
+        self.ignore_case.is_some()
+    }
+
+    /// The text that replaces `matched`
+    pub fn replacement_for(&self, matched: &str, replacement: &str) -> String {
+        if self.preserve_case {
+            preserve_case(matched, replacement)
+        } else {
+            replacement.to_string()
+        }
+    }
+}
+
+impl Editor {
+    /// Case matching of in-buffer search, from the config and the toggle
+    pub(super) fn search_case_matching(&self) -> CaseMatching {
+        match self.search_case_override {
+            Some(true) => CaseMatching::Sensitive,
+            Some(false) => CaseMatching::Insensitive,
+            None => CaseMatching::from_settings(
+                self.config.search.smart_case,
+                self.search_case_sensitive,
+            ),
+        }
+    }
+
+    /// Whether a search for `query` matches case
+    pub(super) fn search_is_case_sensitive(&self, query: &str) -> bool {
+        self.search_case_matching()
+            .is_case_sensitive(query, self.search_use_regex)
+    }
+
+    /// Whether the search options bar shows smart case rather than the
+    /// case-sensitive checkbox
+    pub(super) fn search_smart_case(&self) -> bool {
+        self.config.search.smart_case
+    }
+
+    /// The query of the search or replace prompt being shown
+    pub(super) fn search_prompt_query(&self) -> Option<&str> {
+        let prompt = self.prompt.as_ref()?;
+        match &prompt.prompt_type {
+            PromptType::Search | PromptType::ReplaceSearch | PromptType::QueryReplaceSearch => {
+                Some(&prompt.input)
+            }
+            PromptType::Replace { search } | PromptType::QueryReplace { search } => Some(search),
+            _ => None,
+        }
+    }
+
+    /// Flip whether search matches case. With smart case on, only the current
+    /// search is flipped.
+    pub(super) fn toggle_search_case_sensitive(&mut self) {
+        let query = match self.search_prompt_query() {
+            Some(query) => query.to_string(),
+            None => self
+                .active_search()
+                .map(|search| search.query.clone())
+                .unwrap_or_default(),
+        };
+        let case_sensitive = if self.search_smart_case() {
+            let flipped = !self.search_is_case_sensitive(&query);
+            self.search_case_override = Some(flipped);
+            flipped
+        } else {
+            self.search_case_sensitive = !self.search_case_sensitive;
+            self.search_case_sensitive
+        };
+        let state = if case_sensitive {
+            "enabled"
+        } else {
+            "disabled"
+        };
+        self.set_status_message(t!("search.case_sensitive_state", state = state).to_string());
+    }
+
+    /// Matcher for replacing `search`, literally, in the case matching of the
+    /// search
+    pub(super) fn replace_matcher(&self, search: &str) -> ReplaceMatcher {
+        let case_sensitive = self.search_case_matching().is_case_sensitive(search, false);
+        ReplaceMatcher {
+            search: search.to_string(),
+            ignore_case: if case_sensitive {
+                None
+            } else {
+                Regex::new(&format!("(?i){}", regex::escape(search))).ok()
+            },
+            preserve_case: self.config.search.preserve_case,
+        }
+    }
+
+    /// Text of a match of `matcher` in the active buffer
+    pub(super) fn replace_match_text(
+        &mut self,
+        matcher: &ReplaceMatcher,
+        range: Range<usize>,
+    ) -> String {
+        if matcher.ignores_case() {
+            self.active_state_mut()
+                .get_text_range(range.start, range.end)
+        } else {
+            matcher.search.clone()
+        }
+    }
+}
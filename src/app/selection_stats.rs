@@ -0,0 +1,87 @@
+//! "Selection Statistics"
+//!
+//! The status bar keeps to lines and characters while text is selected
+//! (see [`crate::primitives::selection_stats`]). This command reads the
+//! selected text and shows words, grapheme clusters, bytes and lines in a
+//! popup, broken down by region when several ranges are selected.
+
+use crate::primitives::selection_stats::{selection_regions, SelectionSummary, TextStats};
+use crate::view::popup::{Popup, PopupPosition};
+use ratatui::style::Style;
+use rust_i18n::t;
+
+use super::Editor;
+
+/// Regions listed one by one in the popup; the rest are summed up
+const MAX_LISTED_REGIONS: usize = 100;
+
+impl Editor {
+    /// Show the counts of the active buffer's selection in a popup
+    pub(super) fn show_selection_statistics(&mut self) {
+        let state = self.active_state_mut();
+        let regions = selection_regions(&state.buffer, &state.cursors);
+        let Some(summary) = SelectionSummary::of(&state.buffer, &regions) else {
+            self.set_status_message(t!("selection_stats.no_selection").to_string());
+            return;
+        };
+
+        let mut total = TextStats::default();
+        let mut breakdown = Vec::new();
+        for (index, region) in regions.iter().enumerate() {
+            let stats = TextStats::of(&state.get_text_range(region.start, region.end));
+            total += stats;
+            if regions.len() > 1 && index < MAX_LISTED_REGIONS {
+                let line = state.buffer.get_line_number(region.start);
+                let line_start = state.buffer.line_start_offset(line).unwrap_or(0);
+                breakdown.push(format!(
+                    "  {}",
+                    t!(
+                        "selection_stats.region",
+                        index = index + 1,
+                        line = line + 1,
+                        col = region.start - line_start + 1,
+                        words = stats.words,
+                        chars = stats.characters,
+                        bytes = stats.bytes
+                    )
+                ));
+            }
+        }
+        // Lines shared by several regions count once
+        let lines = summary.lines.unwrap_or(total.lines);
+
+        let mut lines_shown = Vec::new();
+        if summary.regions > 1 {
+            lines_shown.push(t!("selection_stats.regions", count = summary.regions).to_string());
+        }
+        lines_shown.extend([
+            t!("selection_stats.lines", count = lines).to_string(),
+            t!("selection_stats.words", count = total.words).to_string(),
+            t!("selection_stats.characters", count = total.characters).to_string(),
+            t!("selection_stats.bytes", count = total.bytes).to_string(),
+        ]);
+        if !breakdown.is_empty() {
+            lines_shown.push(String::new());
+            lines_shown.push(t!("selection_stats.by_region").to_string());
+            lines_shown.extend(breakdown);
+            if regions.len() > MAX_LISTED_REGIONS {
+                lines_shown.push(format!(
+                    "  {}",
+                    t!(
+                        "selection_stats.more_regions",
+                        count = regions.len() - MAX_LISTED_REGIONS
+                    )
+                ));
+            }
+        }
+
+        let mut popup = Popup::text(lines_shown, &self.theme);
+        popup.title = Some(t!("selection_stats.title").to_string());
+        popup.position = PopupPosition::Centered;
+        popup.width = self.terminal_width.saturating_sub(4).clamp(20, 70);
+        popup.max_height = (self.terminal_height * 60 / 100).clamp(5, 30);
+        popup.border_style = Style::default().fg(self.theme.popup_border_fg);
+        popup.background_style = Style::default().bg(self.theme.popup_bg);
+        self.active_state_mut().popups.show(popup);
+    }
+}
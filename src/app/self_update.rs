@@ -0,0 +1,238 @@
+//! Installing a newer release from inside the editor
+//!
+//! "Update Now" runs [`SelfUpdate`] on the worker pool once the periodic
+//! release check has found a newer version. Its progress replaces one
+//! notification in place; once the new binary is installed a notification
+//! offers to restart into it. Only installs from the release archives are
+//! updated, and only with `updates.self_update` on; package manager installs
+//! are pointed at their own update command.
+
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+
+use rust_i18n::t;
+
+use crate::services::release_checker::{
+    SelfUpdate, SelfUpdateError, SelfUpdateOutcome, SelfUpdateProgress, DEFAULT_DOWNLOAD_URL,
+};
+use crate::services::worker_pool::JobPriority;
+use crate::view::notifications::{
+    next_notification_id, Notification, NotificationAction, NotificationSeverity,
+};
+
+use super::Editor;
+
+/// Action id of the "Restart Now" button
+const RESTART_ACTION: &str = "restart";
+
+/// A self-update running on the worker pool
+pub(crate) struct PendingSelfUpdate {
+    version: String,
+    progress: Receiver<SelfUpdateProgress>,
+    /// Id of the progress notification
+    notification_id: String,
+    /// Last download percentage shown
+    shown_percent: Option<u8>,
+}
+
+impl Editor {
+    /// Download and install the release found by the update check
+    pub(super) fn start_self_update(&mut self) {
+        if self.self_update.is_some() {
+            self.set_status_message(t!("update.in_progress").to_string());
+            return;
+        }
+        if !self.config.updates.self_update {
+            self.set_status_message(t!("update.disabled").to_string());
+            return;
+        }
+        let Some(version) = self.latest_version().map(str::to_string) else {
+            self.set_status_message(t!("update.none_available").to_string());
+            return;
+        };
+        let cache_dir = self.dir_context.cache_dir.join("updates");
+        let update = match SelfUpdate::for_current_exe(&version, DEFAULT_DOWNLOAD_URL, &cache_dir) {
+            Ok(update) => update,
+            Err(e) => {
+                self.report_self_update_error(&version, &e);
+                return;
+            }
+        };
+
+        let (sender, progress) = mpsc::channel();
+        self.worker_pool
+            .submit("self-update", JobPriority::Background, move |token| {
+                let result = update.run(&mut |step| {
+                    let _ = sender.send(step);
+                });
+                if !token.is_cancelled() {
+                    let _ = sender.send(SelfUpdateProgress::Finished(result));
+                }
+                None
+            });
+        let notification_id = next_notification_id();
+        self.post_notification(
+            Notification::new(
+                notification_id.clone(),
+                NotificationSeverity::Info,
+                t!("update.starting", version = version.clone()).to_string(),
+            )
+            .with_timeout(None),
+        );
+        self.self_update = Some(PendingSelfUpdate {
+            version,
+            progress,
+            notification_id,
+            shown_percent: None,
+        });
+    }
+
+    /// Show the progress of a running self-update and report how it ended
+    ///
+    /// Returns true if the screen needs redrawing.
+    pub(super) fn poll_self_update(&mut self) -> bool {
+        let mut changed = false;
+        loop {
+            let Some(pending) = self.self_update.as_mut() else {
+                return changed;
+            };
+            let step = match pending.progress.try_recv() {
+                Ok(step) => step,
+                Err(TryRecvError::Empty) => return changed,
+                Err(TryRecvError::Disconnected) => {
+                    // The job ended without a result (the pool shut down)
+                    let pending = self.self_update.take().unwrap();
+                    self.notifications
+                        .dismiss(&pending.notification_id, self.time_source.now());
+                    return true;
+                }
+            };
+            let message = match step {
+                SelfUpdateProgress::Downloading { received, total } => {
+                    let Some(total) = total.filter(|&total| total > 0) else {
+                        continue;
+                    };
+                    let percent = (received.min(total) * 100 / total) as u8;
+                    if pending.shown_percent == Some(percent) {
+                        continue;
+                    }
+                    pending.shown_percent = Some(percent);
+                    t!(
+                        "update.downloading",
+                        version = pending.version.clone(),
+                        percent = percent
+                    )
+                }
+                SelfUpdateProgress::Verifying => t!("update.verifying"),
+                SelfUpdateProgress::Installing => t!("update.installing"),
+                SelfUpdateProgress::Finished(result) => {
+                    let pending = self.self_update.take().unwrap();
+                    self.finish_self_update(pending, result);
+                    return true;
+                }
+            };
+            let id = pending.notification_id.clone();
+            self.post_notification(
+                Notification::new(id, NotificationSeverity::Info, message.to_string())
+                    .with_timeout(None),
+            );
+            changed = true;
+        }
+    }
+
+    /// Replace the progress notification with the outcome
+    fn finish_self_update(
+        &mut self,
+        pending: PendingSelfUpdate,
+        result: Result<SelfUpdateOutcome, SelfUpdateError>,
+    ) {
+        let version = pending.version;
+        match result {
+            Ok(SelfUpdateOutcome::Installed(exe)) => {
+                self.post_notification(
+                    Notification::new(
+                        pending.notification_id.clone(),
+                        NotificationSeverity::Info,
+                        t!("update.installed", version = version).to_string(),
+                    )
+                    .with_actions(vec![NotificationAction {
+                        id: RESTART_ACTION.to_string(),
+                        label: t!("update.restart_now").to_string(),
+                    }])
+                    .with_timeout(None),
+                );
+                self.self_update_restart = Some((pending.notification_id, exe));
+            }
+            Ok(SelfUpdateOutcome::Staged(_)) => {
+                self.post_notification(Notification::new(
+                    pending.notification_id,
+                    NotificationSeverity::Info,
+                    t!("update.staged", version = version).to_string(),
+                ));
+            }
+            Err(e) => {
+                let now = self.time_source.now();
+                self.notifications.dismiss(&pending.notification_id, now);
+                self.report_self_update_error(&version, &e);
+            }
+        }
+    }
+
+    /// Explain why a self-update failed
+    fn report_self_update_error(&mut self, version: &str, error: &SelfUpdateError) {
+        tracing::warn!("Self-update to {} failed: {}", version, error);
+        let message = match error {
+            SelfUpdateError::ManagedInstall(method) => t!(
+                "update.managed_install",
+                command = method.update_command().unwrap_or_default().trim()
+            ),
+            SelfUpdateError::UnsupportedPlatform => {
+                t!("update.unsupported_platform", version = version)
+            }
+            SelfUpdateError::NotWritable(dir) => {
+                t!("update.not_writable", dir = dir.display().to_string())
+            }
+            SelfUpdateError::ChecksumMissing => t!("update.checksum_missing"),
+            SelfUpdateError::ChecksumMismatch { .. } => t!("update.checksum_mismatch"),
+            other => t!("update.failed", error = other.to_string()),
+        };
+        self.notify(NotificationSeverity::Error, message.to_string());
+    }
+
+    /// Handle the answer to the "update installed" notification
+    ///
+    /// Returns false if the notification wasn't about an installed update.
+    pub(super) fn handle_self_update_answer(
+        &mut self,
+        notification_id: &str,
+        action: Option<&str>,
+    ) -> bool {
+        match &self.self_update_restart {
+            Some((id, _)) if id == notification_id => {}
+            _ => return false,
+        }
+        let (_, exe) = self.self_update_restart.take().unwrap();
+        if action == Some(RESTART_ACTION) {
+            self.restart_into_update(exe);
+        }
+        true
+    }
+
+    /// Quit and run the updated binary, unless there is unsaved work
+    fn restart_into_update(&mut self, exe: PathBuf) {
+        let modified = self.count_modified_buffers();
+        if modified > 0 {
+            self.set_status_message(t!("update.restart_unsaved", count = modified).to_string());
+            return;
+        }
+        tracing::info!("Restarting into updated binary {}", exe.display());
+        self.restart_exe = Some(exe);
+        self.quit();
+    }
+
+    /// Take the binary to run after quitting, set by a restart after a
+    /// self-update
+    pub fn take_restart_exe(&mut self) -> Option<PathBuf> {
+        self.restart_exe.take()
+    }
+}
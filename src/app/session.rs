@@ -9,9 +9,10 @@
 //!
 //! ## Session Save
 //!
-//! [`Editor::save_session`] calls [`Editor::sync_all_terminal_backing_files`] to ensure
+//! [`Editor::start_session_save`] calls [`Editor::sync_all_terminal_backing_files`] to ensure
 //! all terminal backing files contain complete state (scrollback + visible screen)
-//! before serializing session metadata.
+//! before serializing session metadata. The session is then written on the
+//! worker pool.
 //!
 //! ## Session Restore
 //!
@@ -24,77 +25,34 @@
 
 use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
-use std::time::Instant;
+use std::sync::mpsc::RecvTimeoutError;
+use std::time::Duration;
+
+use rust_i18n::t;
 
 use crate::state::EditorState;
 
 use crate::model::event::{BufferId, SplitDirection, SplitId};
+use crate::services::open_target::{self, OpenTarget};
 use crate::services::terminal::TerminalId;
+use crate::services::worker_pool::JobPriority;
 use crate::session::{
-    FileExplorerState, PersistedFileSession, SearchOptions, SerializedBookmark, SerializedCursor,
-    SerializedFileState, SerializedScroll, SerializedSplitDirection, SerializedSplitNode,
+    FileExplorerState, SearchOptions, SerializedBookmark, SerializedCursor, SerializedFileState,
+    SerializedScroll, SerializedSearch, SerializedSplitDirection, SerializedSplitNode,
     SerializedSplitViewState, SerializedTabRef, SerializedTerminalSession, SerializedViewMode,
-    Session, SessionConfigOverrides, SessionError, SessionHistories, SESSION_VERSION,
+    Session, SessionConfigOverrides, SessionError, SessionHistories, SessionWrite,
+    SessionWriteSummary, SESSION_VERSION,
 };
 use crate::state::ViewMode;
+use crate::view::notifications::NotificationSeverity;
 use crate::view::split::{SplitNode, SplitViewState};
 
+use super::session_snapshots::format_snapshot_time;
 use super::types::Bookmark;
 use super::Editor;
 
-/// Session persistence state tracker
-///
-/// Tracks dirty state and handles debounced saving for crash resistance.
-pub struct SessionTracker {
-    /// Whether session has unsaved changes
-    dirty: bool,
-    /// Last save time
-    last_save: Instant,
-    /// Minimum interval between saves (debounce)
-    save_interval: std::time::Duration,
-    /// Whether session persistence is enabled
-    enabled: bool,
-}
-
-impl SessionTracker {
-    /// Create a new session tracker
-    pub fn new(enabled: bool) -> Self {
-        Self {
-            dirty: false,
-            last_save: Instant::now(),
-            save_interval: std::time::Duration::from_secs(5),
-            enabled,
-        }
-    }
-
-    /// Check if session tracking is enabled
-    pub fn is_enabled(&self) -> bool {
-        self.enabled
-    }
-
-    /// Mark session as needing save
-    pub fn mark_dirty(&mut self) {
-        if self.enabled {
-            self.dirty = true;
-        }
-    }
-
-    /// Check if a save is needed and enough time has passed
-    pub fn should_save(&self) -> bool {
-        self.enabled && self.dirty && self.last_save.elapsed() >= self.save_interval
-    }
-
-    /// Record that a save was performed
-    pub fn record_save(&mut self) {
-        self.dirty = false;
-        self.last_save = Instant::now();
-    }
-
-    /// Check if there are unsaved changes (for shutdown)
-    pub fn is_dirty(&self) -> bool {
-        self.dirty
-    }
-}
+/// How long saving the session waits for its write to finish
+pub const SESSION_WRITE_TIMEOUT: Duration = Duration::from_secs(30);
 
 impl Editor {
     /// Capture current editor state into a Session
@@ -146,12 +104,16 @@ impl Editor {
             }
         }
 
+        // Collect persistent plugin panels
+        let (panels, panel_indices) = self.capture_panels();
+
         let split_layout = serialize_split_node(
             self.split_manager.root(),
             &self.buffer_metadata,
             &self.working_dir,
             &self.terminal_buffers,
             &terminal_indices,
+            &panel_indices,
         );
 
         // Build a map of split_id -> active_buffer_id from the split tree
@@ -174,6 +136,7 @@ impl Editor {
                 active_buffer,
                 &self.terminal_buffers,
                 &terminal_indices,
+                &panel_indices,
             );
             tracing::trace!(
                 "Split {:?}: {} open tabs, active_buffer={:?}",
@@ -184,6 +147,18 @@ impl Editor {
             split_states.insert(split_id.0, serialized);
         }
 
+        // Distraction-free mode is saved as the layout it replaced
+        let distraction_free = self.distraction_free.as_ref();
+        if let Some(saved) = distraction_free {
+            if let Some(state) = split_states.get_mut(&saved.split_id.0) {
+                state.compose_width = saved.compose_width;
+            }
+        }
+        let file_explorer_visible =
+            distraction_free.map_or(self.file_explorer_visible, |s| s.file_explorer_visible);
+        let menu_bar_visible =
+            distraction_free.map_or(self.menu_bar_visible, |s| s.menu_bar_visible);
+
         tracing::debug!(
             "Captured {} split states, active_split={}",
             split_states.len(),
@@ -195,14 +170,14 @@ impl Editor {
             // Get expanded directories from the tree
             let expanded_dirs = get_expanded_dirs(explorer, &self.working_dir);
             FileExplorerState {
-                visible: self.file_explorer_visible,
+                visible: file_explorer_visible,
                 width_percent: self.file_explorer_width_percent,
                 expanded_dirs,
                 scroll_offset: explorer.get_scroll_offset(),
             }
         } else {
             FileExplorerState {
-                visible: self.file_explorer_visible,
+                visible: file_explorer_visible,
                 width_percent: self.file_explorer_width_percent,
                 expanded_dirs: Vec::new(),
                 scroll_offset: 0,
@@ -217,7 +192,7 @@ impl Editor {
             syntax_highlighting: Some(self.config.editor.syntax_highlighting),
             enable_inlay_hints: Some(self.config.editor.enable_inlay_hints),
             mouse_enabled: Some(self.mouse_enabled),
-            menu_bar_hidden: Some(!self.menu_bar_visible),
+            menu_bar_hidden: Some(!menu_bar_visible),
         };
 
         // Capture histories using the items() accessor
@@ -229,6 +204,15 @@ impl Editor {
             command_palette: Vec::new(), // Future: when command palette has history
             goto_line: Vec::new(),       // Future: when goto line prompt has history
             open_file: Vec::new(),       // Future: when file open prompt has history
+            clipboard: if self.config.clipboard.save_history_in_session {
+                self.clipboard
+                    .ring()
+                    .iter()
+                    .map(|entry| entry.text().to_string())
+                    .collect()
+            } else {
+                Vec::new()
+            },
         };
         tracing::trace!(
             "Captured histories: {} search, {} replace",
@@ -248,6 +232,9 @@ impl Editor {
         let bookmarks =
             serialize_bookmarks(&self.bookmarks, &self.buffer_metadata, &self.working_dir);
 
+        // Capture pinned files
+        let pinned_files = serialize_pinned_files(&self.buffer_metadata, &self.working_dir);
+
         Session {
             version: SESSION_VERSION,
             working_dir: self.working_dir.clone(),
@@ -260,6 +247,10 @@ impl Editor {
             search_options,
             bookmarks,
             terminals,
+            panels,
+            pinned_files,
+            distraction_free: distraction_free.is_some()
+                && self.config.distraction_free.restore_with_session,
             saved_at: std::time::SystemTime::now()
                 .duration_since(std::time::UNIX_EPOCH)
                 .unwrap_or_default()
@@ -267,51 +258,132 @@ impl Editor {
         }
     }
 
-    /// Save the current session to disk
+    /// Save the current session to disk, waiting for the write to finish
+    ///
+    /// See [`Editor::start_session_save`].
+    pub fn save_session(&mut self) -> Result<SessionWriteSummary, SessionError> {
+        self.start_session_save();
+        self.wait_for_session_save(SESSION_WRITE_TIMEOUT)
+            .unwrap_or_else(|| {
+                Err(SessionError::Io(std::io::Error::new(
+                    std::io::ErrorKind::TimedOut,
+                    "session save is taking too long",
+                )))
+            })
+    }
+
+    /// Start saving the current session on the worker pool
     ///
     /// Ensures all active terminals have their visible screen synced to
-    /// backing files before capturing the session.
-    /// Also saves global file states (scroll/cursor positions per file).
-    pub fn save_session(&mut self) -> Result<(), SessionError> {
+    /// backing files before capturing the session. The session and the
+    /// global file states (scroll/cursor positions per file) are captured
+    /// here and written in the background; only the parts that changed since
+    /// the last save are written, see [`SessionWrite`]. Does nothing unless
+    /// this instance owns the session, see [`Editor::owns_session`].
+    pub fn start_session_save(&mut self) {
+        if !self.owns_session() {
+            tracing::debug!("Not saving session: another instance owns it");
+            return;
+        }
+        // Saves must land in the order they were made
+        if self.session_write.is_some() {
+            let _ = self.wait_for_session_save(SESSION_WRITE_TIMEOUT);
+        }
+
         // Ensure all terminal backing files have complete state before saving
         self.sync_all_terminal_backing_files();
 
-        // Save global file states for all open file buffers
-        self.save_all_global_file_states();
-
-        let session = self.capture_session();
-        session.save()
+        let config = &self.config.session;
+        let write = SessionWrite {
+            session: self.capture_session(),
+            file_states: self.capture_global_file_states(),
+            snapshots: config.snapshots,
+            snapshots_max_bytes: config.snapshots_max_bytes,
+        };
+        let sessions_dir = self.dir_context.sessions_dir();
+        let states_dir = self.dir_context.file_states_dir();
+        let (sender, receiver) = std::sync::mpsc::channel();
+        self.worker_pool
+            .submit("session-save", JobPriority::UserBlocking, move |_token| {
+                let _ = sender.send(write.write(&sessions_dir, &states_dir));
+                None
+            });
+        self.session_write = Some(receiver);
     }
 
-    /// Save global file states for all open file buffers
-    fn save_all_global_file_states(&self) {
-        // Collect all file states from all splits
-        for (split_id, view_state) in &self.split_view_states {
-            // Get the active buffer for this split
-            let active_buffer = self
-                .split_manager
-                .root()
-                .get_leaves_with_rects(ratatui::layout::Rect::default())
-                .into_iter()
-                .find(|(sid, _, _)| *sid == *split_id)
-                .map(|(_, buffer_id, _)| buffer_id);
-
-            if let Some(buffer_id) = active_buffer {
-                self.save_buffer_file_state(buffer_id, view_state);
-            }
+    /// Block for up to `timeout` waiting for the session save started by
+    /// [`Editor::start_session_save`] to finish
+    ///
+    /// Returns `None` while it is still being written, and an empty summary
+    /// when no save was started.
+    pub fn wait_for_session_save(
+        &mut self,
+        timeout: Duration,
+    ) -> Option<Result<SessionWriteSummary, SessionError>> {
+        let Some(receiver) = &self.session_write else {
+            return Some(Ok(SessionWriteSummary::default()));
+        };
+        let result = match receiver.recv_timeout(timeout) {
+            Ok(result) => result,
+            Err(RecvTimeoutError::Timeout) => return None,
+            Err(RecvTimeoutError::Disconnected) => Err(SessionError::Io(std::io::Error::other(
+                "session save was abandoned",
+            ))),
+        };
+        self.session_write = None;
+        if let Ok(summary) = &result {
+            tracing::debug!(
+                "Session save wrote {} file states{}",
+                summary.file_states_written,
+                if summary.session_written {
+                    " and the session file"
+                } else {
+                    ""
+                }
+            );
         }
+        Some(result)
     }
 
-    /// Save file state for a specific buffer (used when closing files and saving session)
-    fn save_buffer_file_state(&self, buffer_id: BufferId, view_state: &SplitViewState) {
-        // Get the file path for this buffer
-        let abs_path = match self.buffer_metadata.get(&buffer_id) {
-            Some(metadata) => match metadata.file_path() {
-                Some(path) => path.to_path_buf(),
-                None => return, // Not a file buffer
-            },
-            None => return,
-        };
+    /// Tell the user the editor is waiting for the session to be written
+    pub fn show_session_saving_note(&mut self) {
+        self.set_status_message(t!("session.saving").to_string());
+    }
+
+    /// Global file states for the active buffer of every split
+    fn capture_global_file_states(&self) -> Vec<(PathBuf, SerializedFileState)> {
+        let leaves = self
+            .split_manager
+            .root()
+            .get_leaves_with_rects(ratatui::layout::Rect::default());
+        self.split_view_states
+            .iter()
+            .filter_map(|(split_id, view_state)| {
+                // Get the active buffer for this split
+                let buffer_id = leaves
+                    .iter()
+                    .find(|(sid, _, _)| sid == split_id)
+                    .map(|(_, buffer_id, _)| *buffer_id)?;
+                // Buffers without their file contents would overwrite the saved position
+                if self.file_loads.contains_key(&buffer_id) {
+                    return None;
+                }
+                self.buffer_file_state(buffer_id, view_state)
+            })
+            .collect()
+    }
+
+    /// File path and state of a buffer shown with `view_state`, for file buffers
+    fn buffer_file_state(
+        &self,
+        buffer_id: BufferId,
+        view_state: &SplitViewState,
+    ) -> Option<(PathBuf, SerializedFileState)> {
+        let abs_path = self
+            .buffer_metadata
+            .get(&buffer_id)?
+            .file_path()?
+            .to_path_buf();
 
         // Capture the current state
         let primary_cursor = view_state.cursors.primary();
@@ -337,9 +409,7 @@ impl Editor {
                 left_column: view_state.viewport.left_column,
             },
         };
-
-        // Save to disk immediately
-        PersistedFileSession::save(&abs_path, file_state);
+        Some((abs_path, file_state))
     }
 
     /// Sync all active terminal visible screens to their backing files.
@@ -387,9 +457,82 @@ impl Editor {
     /// Try to load and apply a session for the current working directory
     ///
     /// Returns true if a session was successfully loaded and applied.
+    /// A session file that cannot be read is replaced by its newest readable
+    /// snapshot, with a status message saying so. Plugins get a single
+    /// `layout_restored` hook rather than one per split the session adds.
     pub fn try_restore_session(&mut self) -> Result<bool, SessionError> {
+        self.begin_layout_restore();
+        let result = self.restore_session();
+        self.finish_layout_restore("session", matches!(result, Ok(true)));
+        result
+    }
+
+    fn restore_session(&mut self) -> Result<bool, SessionError> {
         tracing::debug!("Attempting to restore session for {:?}", self.working_dir);
-        match Session::load(&self.working_dir)? {
+        let sessions_dir = self.dir_context.sessions_dir();
+        let mut session = match Session::load(&sessions_dir, &self.working_dir) {
+            Err(SessionError::VersionTooNew {
+                version,
+                max_supported,
+            }) => {
+                // Leave the file alone; saving backs it up under its version
+                tracing::warn!(
+                    "Session file version {} is newer than supported {}, starting fresh",
+                    version,
+                    max_supported
+                );
+                self.set_status_message(
+                    t!(
+                        "session.version_too_new",
+                        version = version,
+                        supported = max_supported
+                    )
+                    .to_string(),
+                );
+                return Ok(false);
+            }
+            Err(e @ (SessionError::Io(_) | SessionError::Json(_))) => {
+                let Some(snapshot) =
+                    Session::load_newest_snapshot(&sessions_dir, &self.working_dir)
+                else {
+                    if let Some(backup) = Session::load_backup(&sessions_dir, &self.working_dir) {
+                        tracing::warn!("Session file is unreadable ({}), restoring backup", e);
+                        self.apply_session(&backup)?;
+                        self.notify(
+                            NotificationSeverity::Warning,
+                            t!("session.restored_from_backup"),
+                        );
+                        return Ok(true);
+                    }
+                    tracing::warn!("Session file is unreadable ({}), starting fresh", e);
+                    self.set_status_message(t!("session.unreadable").to_string());
+                    return Ok(false);
+                };
+                tracing::warn!(
+                    "Session file is unreadable ({}), restoring snapshot {:?}",
+                    e,
+                    snapshot.path
+                );
+                self.apply_session(&snapshot.session)?;
+                self.notify(
+                    NotificationSeverity::Warning,
+                    t!(
+                        "session.restored_from_snapshot",
+                        time = format_snapshot_time(snapshot.session.saved_at)
+                    ),
+                );
+                return Ok(true);
+            }
+            result => result?,
+        };
+        // Until the user has answered the migration prompt, the session may
+        // still be in the legacy state directory
+        if session.is_none() {
+            if let Some(migration) = self.dir_context.pending_state_migration() {
+                session = Session::load(&migration.from.join("sessions"), &self.working_dir)?;
+            }
+        }
+        match session {
             Some(session) => {
                 tracing::info!("Found session, applying...");
                 self.apply_session(&session)?;
@@ -450,6 +593,12 @@ impl Editor {
         for item in &session.histories.replace {
             self.replace_history.push(item.clone());
         }
+        if self.config.clipboard.save_history_in_session {
+            // Oldest first, so the newest ends up at the front
+            for text in session.histories.clipboard.iter().rev() {
+                self.clipboard.ring_mut().push(text);
+            }
+        }
 
         // 4. Restore file explorer state
         self.file_explorer_visible = session.file_explorer.visible;
@@ -473,12 +622,12 @@ impl Editor {
 
         for rel_path in file_paths {
             let abs_path = self.working_dir.join(&rel_path);
-            tracing::trace!(
-                "Checking file: {:?} (exists: {})",
-                abs_path,
-                abs_path.exists()
-            );
-            if abs_path.exists() {
+            let target = open_target::classify(&abs_path);
+            tracing::trace!("Checking file: {:?} ({:?})", abs_path, target);
+            if matches!(target, Ok(OpenTarget::File)) {
+                let _phase = crate::services::startup_profile::phase_with(|| {
+                    format!("buffer {}", rel_path.display())
+                });
                 // Open the file (this will reuse existing buffer if already open)
                 match self.open_file_internal(&abs_path) {
                     Ok(buffer_id) => {
@@ -490,11 +639,13 @@ impl Editor {
                     }
                 }
             } else {
-                tracing::debug!("Skipping non-existent file: {:?}", abs_path);
+                // Directories, FIFOs and devices aren't reopened as buffers
+                tracing::debug!("Skipping {:?}: {:?}", abs_path, target);
             }
         }
 
         tracing::debug!("Opened {} files from session", path_to_buffer.len());
+        crate::services::startup_profile::count("buffers", path_to_buffer.len());
 
         // Restore terminals and build index -> buffer map
         let mut terminal_buffer_map: HashMap<usize, BufferId> = HashMap::new();
@@ -509,6 +660,13 @@ impl Editor {
             }
         }
 
+        // Recreate persistent plugin panels as placeholders
+        let mut panel_buffer_map: HashMap<usize, BufferId> = HashMap::new();
+        for panel in &session.panels {
+            let buffer_id = self.restore_panel_from_session(panel);
+            panel_buffer_map.insert(panel.panel_index, buffer_id);
+        }
+
         // 6. Rebuild split layout from the saved tree
         // Map old split IDs to new ones as we create splits
         let mut split_id_map: HashMap<usize, SplitId> = HashMap::new();
@@ -516,11 +674,20 @@ impl Editor {
             &session.split_layout,
             &path_to_buffer,
             &terminal_buffer_map,
+            &panel_buffer_map,
             &session.split_states,
             &mut split_id_map,
             true, // is_first_leaf - the first leaf reuses the existing split
         );
 
+        // Now that the panels have splits, let their plugins fill them
+        let panel_buffers: Vec<BufferId> = session
+            .panels
+            .iter()
+            .filter_map(|panel| panel_buffer_map.get(&panel.panel_index).copied())
+            .collect();
+        self.request_panel_restores(&panel_buffers);
+
         // Set the active split based on the saved active_split_id
         // NOTE: active_buffer is now derived from split_manager, which was already
         // correctly set up by restore_split_view_state() via set_split_buffer()
@@ -528,6 +695,10 @@ impl Editor {
             self.split_manager.set_active_split(new_active_split);
         }
 
+        if session.distraction_free && self.config.distraction_free.restore_with_session {
+            self.enter_distraction_free();
+        }
+
         // 7. Restore bookmarks
         for (key, bookmark) in &session.bookmarks {
             if let Some(&buffer_id) = path_to_buffer.get(&bookmark.file_path) {
@@ -545,6 +716,13 @@ impl Editor {
             }
         }
 
+        // 8. Restore pins
+        for rel_path in &session.pinned_files {
+            if let Some(&buffer_id) = path_to_buffer.get(rel_path) {
+                self.set_buffer_pinned(buffer_id, true);
+            }
+        }
+
         tracing::debug!(
             "Session restore complete: {} splits, {} buffers",
             self.split_view_states.len(),
@@ -681,11 +859,13 @@ impl Editor {
     }
 
     /// Recursively restore the split layout from a serialized tree
-    fn restore_split_node(
+    #[allow(clippy::too_many_arguments)]
+    pub(super) fn restore_split_node(
         &mut self,
         node: &SerializedSplitNode,
         path_to_buffer: &HashMap<PathBuf, BufferId>,
         terminal_buffers: &HashMap<usize, BufferId>,
+        panel_buffers: &HashMap<usize, BufferId>,
         split_states: &HashMap<usize, SerializedSplitViewState>,
         split_id_map: &mut HashMap<usize, SplitId>,
         is_first_leaf: bool,
@@ -721,6 +901,7 @@ impl Editor {
                     split_states,
                     path_to_buffer,
                     terminal_buffers,
+                    panel_buffers,
                 );
             }
             SerializedSplitNode::Terminal {
@@ -752,6 +933,32 @@ impl Editor {
                     split_states,
                     path_to_buffer,
                     terminal_buffers,
+                    panel_buffers,
+                );
+            }
+            SerializedSplitNode::Panel {
+                panel_index,
+                split_id,
+            } => {
+                let buffer_id = panel_buffers
+                    .get(panel_index)
+                    .copied()
+                    .unwrap_or(self.active_buffer());
+
+                let current_split_id = self.split_manager.active_split();
+                split_id_map.insert(*split_id, current_split_id);
+
+                let _ = self
+                    .split_manager
+                    .set_split_buffer(current_split_id, buffer_id);
+
+                self.restore_split_view_state(
+                    current_split_id,
+                    *split_id,
+                    split_states,
+                    path_to_buffer,
+                    terminal_buffers,
+                    panel_buffers,
                 );
             }
             SerializedSplitNode::Split {
@@ -766,6 +973,7 @@ impl Editor {
                     first,
                     path_to_buffer,
                     terminal_buffers,
+                    panel_buffers,
                     split_states,
                     split_id_map,
                     is_first_leaf,
@@ -773,7 +981,7 @@ impl Editor {
 
                 // Get the buffer for the second child's first leaf
                 let second_buffer_id =
-                    get_first_leaf_buffer(second, path_to_buffer, terminal_buffers)
+                    get_first_leaf_buffer(second, path_to_buffer, terminal_buffers, panel_buffers)
                         .unwrap_or(self.active_buffer());
 
                 // Convert direction
@@ -805,6 +1013,7 @@ impl Editor {
                             second,
                             path_to_buffer,
                             terminal_buffers,
+                            panel_buffers,
                             split_states,
                             split_id_map,
                             false,
@@ -826,6 +1035,7 @@ impl Editor {
         split_states: &HashMap<usize, SerializedSplitViewState>,
         path_to_buffer: &HashMap<PathBuf, BufferId>,
         terminal_buffers: &HashMap<usize, BufferId>,
+        panel_buffers: &HashMap<usize, BufferId>,
     ) {
         // Try to find the saved state for this split
         let Some(split_state) = split_states.get(&saved_split_id) else {
@@ -859,6 +1069,13 @@ impl Editor {
                             view_state.viewport.line_wrap_enabled = false;
                         }
                     }
+                    SerializedTabRef::Panel(index) => {
+                        if let Some(&buffer_id) = panel_buffers.get(index) {
+                            if !view_state.open_buffers.contains(&buffer_id) {
+                                view_state.open_buffers.push(buffer_id);
+                            }
+                        }
+                    }
                 }
             }
 
@@ -867,6 +1084,7 @@ impl Editor {
                     active_buffer_id = match tab {
                         SerializedTabRef::File(rel) => path_to_buffer.get(rel).copied(),
                         SerializedTabRef::Terminal(index) => terminal_buffers.get(index).copied(),
+                        SerializedTabRef::Panel(index) => panel_buffers.get(index).copied(),
                     };
                 }
             }
@@ -891,44 +1109,9 @@ impl Editor {
             for (rel_path, file_state) in &split_state.file_states {
                 let buffer_for_path = path_to_buffer.get(rel_path).copied();
                 if buffer_for_path == Some(active_id) {
-                    if let Some(buffer) = self.buffers.get(&active_id) {
-                        let max_pos = buffer.buffer.len();
-                        let cursor_pos = file_state.cursor.position.min(max_pos);
-
-                        // Set cursor in SplitViewState
-                        view_state.cursors.primary_mut().position = cursor_pos;
-                        view_state.cursors.primary_mut().anchor =
-                            file_state.cursor.anchor.map(|a| a.min(max_pos));
-                        view_state.cursors.primary_mut().sticky_column =
-                            file_state.cursor.sticky_column;
-
-                        // Set scroll position
-                        view_state.viewport.top_byte = file_state.scroll.top_byte.min(max_pos);
-                        view_state.viewport.top_view_line_offset =
-                            file_state.scroll.top_view_line_offset;
-                        view_state.viewport.left_column = file_state.scroll.left_column;
-                        // Mark viewport to skip sync on first resize after session restore
-                        // This prevents ensure_visible from overwriting the restored scroll position
-                        view_state.viewport.set_skip_resize_sync();
-
-                        tracing::trace!(
-                            "Restored SplitViewState for {:?}: cursor={}, top_byte={}",
-                            rel_path,
-                            cursor_pos,
-                            view_state.viewport.top_byte
-                        );
-                    }
-
-                    // Also set cursor in EditorState (authoritative for cursors)
-                    if let Some(editor_state) = self.buffers.get_mut(&active_id) {
-                        let max_pos = editor_state.buffer.len();
-                        let cursor_pos = file_state.cursor.position.min(max_pos);
-                        editor_state.cursors.primary_mut().position = cursor_pos;
-                        editor_state.cursors.primary_mut().anchor =
-                            file_state.cursor.anchor.map(|a| a.min(max_pos));
-                        editor_state.cursors.primary_mut().sticky_column =
-                            file_state.cursor.sticky_column;
-                        // Note: viewport is now exclusively owned by SplitViewState (restored above)
+                    tracing::trace!("Restoring file state for {:?}", rel_path);
+                    if !self.defer_restored_file_state(active_id, current_split_id, file_state) {
+                        self.apply_restored_file_state(current_split_id, active_id, file_state);
                     }
                     break;
                 }
@@ -940,6 +1123,10 @@ impl Editor {
                 .set_split_buffer(current_split_id, active_id);
         }
 
+        let Some(view_state) = self.split_view_states.get_mut(&current_split_id) else {
+            return;
+        };
+
         // Restore view mode
         view_state.view_mode = match split_state.view_mode {
             SerializedViewMode::Source => ViewMode::Source,
@@ -947,14 +1134,74 @@ impl Editor {
         };
         view_state.compose_width = split_state.compose_width;
         view_state.tab_scroll_offset = split_state.tab_scroll_offset;
+        view_state.input_locked = split_state.input_locked;
+
+        // Re-run the split's last search, whose matches are not saved
+        if let Some(search) = &split_state.search {
+            let restored = self.search_in_split(
+                current_split_id,
+                &search.query,
+                search.case_sensitive,
+                search.whole_word,
+                search.use_regex,
+            );
+            if let Some(view_state) = self.split_view_states.get_mut(&current_split_id) {
+                view_state.search = restored;
+            }
+        }
+    }
+
+    /// Apply a saved cursor and scroll position to a buffer shown in a split
+    pub(super) fn apply_restored_file_state(
+        &mut self,
+        split_id: SplitId,
+        buffer_id: BufferId,
+        file_state: &SerializedFileState,
+    ) {
+        let Some(max_pos) = self.buffers.get(&buffer_id).map(|s| s.buffer.len()) else {
+            return;
+        };
+        let cursor_pos = file_state.cursor.position.min(max_pos);
+        let anchor = file_state.cursor.anchor.map(|a| a.min(max_pos));
+
+        if let Some(view_state) = self.split_view_states.get_mut(&split_id) {
+            // Set cursor in SplitViewState
+            view_state.cursors.primary_mut().position = cursor_pos;
+            view_state.cursors.primary_mut().anchor = anchor;
+            view_state.cursors.primary_mut().sticky_column = file_state.cursor.sticky_column;
+
+            // Set scroll position
+            view_state.viewport.top_byte = file_state.scroll.top_byte.min(max_pos);
+            view_state.viewport.top_view_line_offset = file_state.scroll.top_view_line_offset;
+            view_state.viewport.left_column = file_state.scroll.left_column;
+            // Mark viewport to skip sync on first resize after session restore
+            // This prevents ensure_visible from overwriting the restored scroll position
+            view_state.viewport.set_skip_resize_sync();
+
+            tracing::trace!(
+                "Restored SplitViewState for {:?}: cursor={}, top_byte={}",
+                buffer_id,
+                cursor_pos,
+                view_state.viewport.top_byte
+            );
+        }
+
+        // Also set cursor in EditorState (authoritative for cursors)
+        // Note: viewport is exclusively owned by SplitViewState (restored above)
+        if let Some(editor_state) = self.buffers.get_mut(&buffer_id) {
+            editor_state.cursors.primary_mut().position = cursor_pos;
+            editor_state.cursors.primary_mut().anchor = anchor;
+            editor_state.cursors.primary_mut().sticky_column = file_state.cursor.sticky_column;
+        }
     }
 }
 
 /// Helper: Get the buffer ID from the first leaf node in a split tree
-fn get_first_leaf_buffer(
+pub(super) fn get_first_leaf_buffer(
     node: &SerializedSplitNode,
     path_to_buffer: &HashMap<PathBuf, BufferId>,
     terminal_buffers: &HashMap<usize, BufferId>,
+    panel_buffers: &HashMap<usize, BufferId>,
 ) -> Option<BufferId> {
     match node {
         SerializedSplitNode::Leaf { file_path, .. } => file_path
@@ -963,8 +1210,9 @@ fn get_first_leaf_buffer(
         SerializedSplitNode::Terminal { terminal_index, .. } => {
             terminal_buffers.get(terminal_index).copied()
         }
+        SerializedSplitNode::Panel { panel_index, .. } => panel_buffers.get(panel_index).copied(),
         SerializedSplitNode::Split { first, .. } => {
-            get_first_leaf_buffer(first, path_to_buffer, terminal_buffers)
+            get_first_leaf_buffer(first, path_to_buffer, terminal_buffers, panel_buffers)
         }
     }
 }
@@ -979,6 +1227,7 @@ fn serialize_split_node(
     working_dir: &Path,
     terminal_buffers: &HashMap<BufferId, TerminalId>,
     terminal_indices: &HashMap<TerminalId, usize>,
+    panel_indices: &HashMap<BufferId, usize>,
 ) -> SerializedSplitNode {
     match node {
         SplitNode::Leaf {
@@ -993,6 +1242,12 @@ fn serialize_split_node(
                     };
                 }
             }
+            if let Some(index) = panel_indices.get(buffer_id) {
+                return SerializedSplitNode::Panel {
+                    panel_index: *index,
+                    split_id: split_id.0,
+                };
+            }
 
             let file_path = buffer_metadata
                 .get(buffer_id)
@@ -1026,6 +1281,7 @@ fn serialize_split_node(
                 working_dir,
                 terminal_buffers,
                 terminal_indices,
+                panel_indices,
             )),
             second: Box::new(serialize_split_node(
                 second,
@@ -1033,6 +1289,7 @@ fn serialize_split_node(
                 working_dir,
                 terminal_buffers,
                 terminal_indices,
+                panel_indices,
             )),
             ratio: *ratio,
             split_id: split_id.0,
@@ -1047,6 +1304,7 @@ fn serialize_split_view_state(
     active_buffer: Option<BufferId>,
     terminal_buffers: &HashMap<BufferId, TerminalId>,
     terminal_indices: &HashMap<TerminalId, usize>,
+    panel_indices: &HashMap<BufferId, usize>,
 ) -> SerializedSplitViewState {
     let mut open_tabs = Vec::new();
     let mut open_files = Vec::new();
@@ -1063,6 +1321,13 @@ fn serialize_split_view_state(
                 continue;
             }
         }
+        if let Some(idx) = panel_indices.get(buffer_id) {
+            open_tabs.push(SerializedTabRef::Panel(*idx));
+            if Some(*buffer_id) == active_buffer {
+                active_tab_index = Some(tab_index);
+            }
+            continue;
+        }
 
         if let Some(rel_path) = buffer_metadata
             .get(buffer_id)
@@ -1138,6 +1403,13 @@ fn serialize_split_view_state(
             ViewMode::Compose => SerializedViewMode::Compose,
         },
         compose_width: view_state.compose_width,
+        input_locked: view_state.input_locked,
+        search: view_state.search.as_ref().map(|search| SerializedSearch {
+            query: search.query.clone(),
+            case_sensitive: search.case_sensitive,
+            whole_word: search.whole_word,
+            use_regex: search.use_regex,
+        }),
     }
 }
 
@@ -1167,12 +1439,30 @@ fn serialize_bookmarks(
         .collect()
 }
 
-/// Collect all unique file paths from split_states
-fn collect_file_paths_from_states(
+/// Relative paths of the pinned file buffers, sorted
+fn serialize_pinned_files(
+    buffer_metadata: &HashMap<BufferId, super::types::BufferMetadata>,
+    working_dir: &Path,
+) -> Vec<PathBuf> {
+    let mut paths: Vec<PathBuf> = buffer_metadata
+        .values()
+        .filter(|meta| meta.pinned)
+        .filter_map(|meta| meta.file_path())
+        .filter_map(|abs_path| abs_path.strip_prefix(working_dir).ok())
+        .map(Path::to_path_buf)
+        .collect();
+    paths.sort();
+    paths
+}
+
+/// Collect all unique file paths from split_states, in split order
+pub(super) fn collect_file_paths_from_states(
     split_states: &HashMap<usize, SerializedSplitViewState>,
 ) -> Vec<PathBuf> {
+    let mut split_ids: Vec<usize> = split_states.keys().copied().collect();
+    split_ids.sort_unstable();
     let mut paths = Vec::new();
-    for state in split_states.values() {
+    for state in split_ids.iter().map(|id| &split_states[id]) {
         if !state.open_tabs.is_empty() {
             for tab in &state.open_tabs {
                 if let SerializedTabRef::File(path) = tab {
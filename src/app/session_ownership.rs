@@ -0,0 +1,257 @@
+//! Which instance persists the session of a working directory
+//!
+//! Two editors open in the same directory would both write its session file
+//! and recovery files, and the last one to exit would silently win. At
+//! startup an editor claims the session with an [`InstanceMarker`]; if
+//! another running instance already owns it, a popup asks whether to
+//! continue without saving the session, take it over, or open without it.
+//! Only the owner saves the session and writes recovery files; the others
+//! can still edit and save files as usual.
+
+use rust_i18n::t;
+
+use crate::session::InstanceMarker;
+use crate::view::notifications::NotificationSeverity;
+
+use super::Editor;
+
+/// Popup item data for "Continue"
+const CONTINUE_ACTION: &str = "continue";
+/// Popup item data for "Take over session ownership"
+const TAKE_OVER_ACTION: &str = "take_over";
+/// Popup item data for "Open without session"
+const NO_SESSION_ACTION: &str = "no_session";
+
+/// Whether this editor persists the session of its working directory
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SessionOwnership {
+    /// Saves the session and writes recovery files
+    Owner,
+    /// Another instance owns the session and the popup hasn't been answered
+    Undecided,
+    /// Restored the session but doesn't save it
+    Guest,
+    /// Neither restored nor saves the session
+    Detached,
+}
+
+/// The ownership popup and what to do once it is answered
+#[derive(Debug)]
+pub(super) struct PendingSessionOwnership {
+    /// Title of the popup, used to recognize it
+    popup_title: String,
+    /// Whether to restore the session unless "Open without session" is chosen
+    restore_session: bool,
+}
+
+impl Editor {
+    /// Claim the session of the working directory for this instance
+    ///
+    /// Returns true if this instance owns it, and false if another running
+    /// instance does; the popup asking what to do is then shown, and
+    /// answering it restores the session if `restore_session` is set.
+    pub fn claim_session_ownership(&mut self, restore_session: bool) -> bool {
+        let sessions_dir = self.dir_context.sessions_dir();
+        match InstanceMarker::read_owner(&sessions_dir, &self.working_dir) {
+            Some(owner) if owner != self.instance_marker => {
+                tracing::info!(
+                    "Session of {:?} is owned by pid {}",
+                    self.working_dir,
+                    owner.pid
+                );
+                self.session_ownership = SessionOwnership::Undecided;
+                self.offer_session_ownership(&owner, restore_session);
+                false
+            }
+            _ => {
+                self.take_session_ownership();
+                true
+            }
+        }
+    }
+
+    /// Write the instance marker and become the owner
+    fn take_session_ownership(&mut self) {
+        let sessions_dir = self.dir_context.sessions_dir();
+        if let Err(e) = self.instance_marker.write(&sessions_dir, &self.working_dir) {
+            tracing::warn!("Failed to write instance marker: {}", e);
+        }
+        self.session_ownership = SessionOwnership::Owner;
+    }
+
+    /// Give up ownership on exit, so the next instance can take it
+    pub fn release_session_ownership(&mut self) {
+        if self.session_ownership != SessionOwnership::Owner {
+            return;
+        }
+        let sessions_dir = self.dir_context.sessions_dir();
+        if let Err(e) = self
+            .instance_marker
+            .remove(&sessions_dir, &self.working_dir)
+        {
+            tracing::warn!("Failed to remove instance marker: {}", e);
+        }
+    }
+
+    /// Whether this instance may save the session and write recovery files
+    ///
+    /// An owner whose session was taken over by another instance stops
+    /// persisting state and is told so once.
+    pub fn owns_session(&mut self) -> bool {
+        if self.session_ownership != SessionOwnership::Owner {
+            return false;
+        }
+        let sessions_dir = self.dir_context.sessions_dir();
+        match InstanceMarker::read_owner(&sessions_dir, &self.working_dir) {
+            Some(owner) if owner != self.instance_marker => {
+                tracing::warn!("Session was taken over by pid {}", owner.pid);
+                self.session_ownership = SessionOwnership::Guest;
+                self.notify(
+                    NotificationSeverity::Warning,
+                    t!("session.taken_over", pid = owner.pid),
+                );
+                false
+            }
+            _ => true,
+        }
+    }
+
+    /// Current ownership of the session
+    pub fn session_ownership(&self) -> SessionOwnership {
+        self.session_ownership
+    }
+
+    /// Ask what to do about a session owned by another instance
+    fn offer_session_ownership(&mut self, owner: &InstanceMarker, restore_session: bool) {
+        use crate::model::event::{
+            PopupContentData, PopupData, PopupListItemData, PopupPositionData,
+        };
+
+        let title = t!("session.conflict_title").to_string();
+        let item = |text: &str, detail: &str, action: &str| PopupListItemData {
+            text: text.to_string(),
+            detail: Some(detail.to_string()),
+            icon: None,
+            data: Some(action.to_string()),
+        };
+        let popup = PopupData {
+            title: Some(title.clone()),
+            description: Some(
+                t!(
+                    "session.conflict_description",
+                    pid = owner.pid,
+                    dir = self.working_dir.display().to_string()
+                )
+                .to_string(),
+            ),
+            transient: false,
+            content: PopupContentData::List {
+                items: vec![
+                    item(
+                        &t!("session.conflict_continue"),
+                        &t!("session.conflict_continue_detail"),
+                        CONTINUE_ACTION,
+                    ),
+                    item(
+                        &t!("session.conflict_take_over"),
+                        &t!("session.conflict_take_over_detail"),
+                        TAKE_OVER_ACTION,
+                    ),
+                    item(
+                        &t!("session.conflict_no_session"),
+                        &t!("session.conflict_no_session_detail"),
+                        NO_SESSION_ACTION,
+                    ),
+                ],
+                selected: 0,
+            },
+            position: PopupPositionData::Centered,
+            width: 70,
+            max_height: 10,
+            bordered: true,
+        };
+
+        self.pending_session_ownership = Some(PendingSessionOwnership {
+            popup_title: title,
+            restore_session,
+        });
+        self.show_popup(popup);
+    }
+
+    /// Whether the topmost popup is the session ownership popup
+    pub(super) fn is_session_ownership_popup(&self) -> bool {
+        let Some(pending) = &self.pending_session_ownership else {
+            return false;
+        };
+        self.active_state()
+            .popups
+            .top()
+            .and_then(|popup| popup.title.as_ref())
+            .is_some_and(|title| *title == pending.popup_title)
+    }
+
+    /// Handle the user's choice in the session ownership popup
+    ///
+    /// `None` means the popup was dismissed, which opens without the session.
+    pub(super) fn handle_session_ownership_response(&mut self, action: Option<&str>) {
+        let Some(pending) = self.pending_session_ownership.take() else {
+            return;
+        };
+        let restore = match action {
+            Some(CONTINUE_ACTION) => {
+                self.session_ownership = SessionOwnership::Guest;
+                self.set_status_message(t!("session.continuing_as_guest").to_string());
+                pending.restore_session
+            }
+            Some(TAKE_OVER_ACTION) => {
+                self.take_session_ownership();
+                if let Err(e) = self.start_recovery_session() {
+                    tracing::warn!("Failed to start recovery session: {}", e);
+                }
+                self.set_status_message(t!("session.took_over").to_string());
+                pending.restore_session
+            }
+            _ => {
+                self.session_ownership = SessionOwnership::Detached;
+                self.set_status_message(t!("session.opened_without").to_string());
+                false
+            }
+        };
+        if restore {
+            if let Err(e) = self.try_restore_session() {
+                tracing::warn!("Failed to restore session: {}", e);
+            }
+        }
+    }
+
+    /// Show who persists the session of the working directory
+    pub(super) fn show_session_ownership(&mut self) {
+        let sessions_dir = self.dir_context.sessions_dir();
+        let owner = match InstanceMarker::read_owner(&sessions_dir, &self.working_dir) {
+            Some(owner) => t!("session.owner_pid", pid = owner.pid).to_string(),
+            None => t!("session.owner_none").to_string(),
+        };
+        let pid = self.instance_marker.pid;
+        let dir = self.working_dir.display().to_string();
+        let message = match self.session_ownership {
+            SessionOwnership::Owner => t!("session.ownership_owner", pid = pid, dir = dir),
+            SessionOwnership::Undecided | SessionOwnership::Guest => {
+                t!(
+                    "session.ownership_guest",
+                    pid = pid,
+                    dir = dir,
+                    owner = owner
+                )
+            }
+            SessionOwnership::Detached => {
+                t!(
+                    "session.ownership_detached",
+                    pid = pid,
+                    dir = dir,
+                    owner = owner
+                )
+            }
+        };
+        self.set_status_message(message.to_string());
+    }
+}
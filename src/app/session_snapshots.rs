@@ -0,0 +1,201 @@
+//! Restoring the session from a snapshot
+//!
+//! Every session save also keeps a timestamped snapshot (see
+//! [`Session::save_snapshot`]). "Restore Session from Snapshot" lists them with
+//! the files each one opens, and replaces the current layout with the chosen
+//! one through the normal session restore path, after the usual confirmation
+//! when buffers have unsaved changes.
+
+use std::path::Path;
+
+use rust_i18n::t;
+
+use super::Editor;
+use crate::input::commands::Suggestion;
+use crate::input::keybindings::KeyContext;
+use crate::model::event::BufferId;
+use crate::session::Session;
+use crate::view::notifications::NotificationSeverity;
+use crate::view::prompt::{Prompt, PromptType};
+
+/// Files listed in a snapshot's description before the rest are counted
+const MAX_LISTED_FILES: usize = 8;
+
+/// Format a session's `saved_at` (Unix seconds) in local time
+pub(super) fn format_snapshot_time(saved_at: u64) -> String {
+    chrono::DateTime::from_timestamp(saved_at as i64, 0)
+        .map(|time| {
+            time.with_timezone(&chrono::Local)
+                .format("%Y-%m-%d %H:%M:%S")
+                .to_string()
+        })
+        .unwrap_or_else(|| saved_at.to_string())
+}
+
+impl Editor {
+    /// Show a picker of the session snapshots for the working directory
+    pub fn start_restore_session_snapshot_prompt(&mut self) {
+        let snapshots =
+            match Session::list_snapshots(&self.dir_context.sessions_dir(), &self.working_dir) {
+                Ok(snapshots) => snapshots,
+                Err(e) => {
+                    self.set_status_message(
+                        t!("session.snapshot_failed", error = e.to_string()).to_string(),
+                    );
+                    return;
+                }
+            };
+        if snapshots.is_empty() {
+            self.set_status_message(t!("session.no_snapshots").to_string());
+            return;
+        }
+
+        let suggestions: Vec<Suggestion> = snapshots
+            .iter()
+            .map(|snapshot| {
+                let paths = snapshot.files();
+                let mut listed: Vec<String> = paths
+                    .iter()
+                    .take(MAX_LISTED_FILES)
+                    .map(|path| path.display().to_string())
+                    .collect();
+                if paths.len() > MAX_LISTED_FILES {
+                    listed.push(
+                        t!(
+                            "session.snapshot_more_files",
+                            count = paths.len() - MAX_LISTED_FILES
+                        )
+                        .to_string(),
+                    );
+                }
+                let files = if listed.is_empty() {
+                    t!("session.snapshot_no_files").to_string()
+                } else {
+                    listed.join(", ")
+                };
+                Suggestion {
+                    text: format_snapshot_time(snapshot.session.saved_at),
+                    description: Some(
+                        t!(
+                            "session.snapshot_entry",
+                            count = snapshot.buffer_count(),
+                            dir = snapshot.session.working_dir.display().to_string(),
+                            files = files
+                        )
+                        .to_string(),
+                    ),
+                    value: Some(snapshot.path.display().to_string()),
+                    disabled: false,
+                    keybinding: None,
+                    source: None,
+                }
+            })
+            .collect();
+
+        self.prompt = Some(Prompt::with_suggestions(
+            t!("session.snapshot_prompt").to_string(),
+            PromptType::RestoreSessionSnapshot,
+            suggestions,
+        ));
+        if let Some(prompt) = self.prompt.as_mut() {
+            prompt.selected_suggestion = Some(0);
+        }
+    }
+
+    /// Restore the snapshot chosen in the picker, confirming first if any
+    /// buffer has unsaved changes
+    pub(super) fn restore_session_snapshot(&mut self, path: std::path::PathBuf) {
+        if path.as_os_str().is_empty() {
+            return;
+        }
+        let modified_count = self
+            .buffers
+            .values()
+            .filter(|state| state.buffer.is_modified())
+            .count();
+        if modified_count == 0 {
+            self.apply_session_snapshot(&path);
+            return;
+        }
+
+        let discard_key = t!("prompt.key.discard").to_string();
+        let cancel_key = t!("prompt.key.cancel").to_string();
+        self.start_prompt(
+            t!(
+                "session.snapshot_modified",
+                count = modified_count,
+                discard_key = discard_key,
+                cancel_key = cancel_key
+            )
+            .to_string(),
+            PromptType::ConfirmRestoreSessionSnapshot { path },
+        );
+    }
+
+    /// Replace the current layout and buffers with a snapshot's
+    pub(super) fn apply_session_snapshot(&mut self, path: &Path) {
+        let session = match Session::load_from_path(path, &self.working_dir) {
+            Ok(session) => session,
+            Err(e) => {
+                self.set_status_message(
+                    t!("session.snapshot_failed", error = e.to_string()).to_string(),
+                );
+                return;
+            }
+        };
+
+        self.begin_layout_restore();
+        self.clear_layout_for_session();
+        let result = self.apply_session(&session);
+        self.finish_layout_restore("snapshot", result.is_ok());
+        if let Err(e) = result {
+            self.set_status_message(
+                t!("session.snapshot_failed", error = e.to_string()).to_string(),
+            );
+            return;
+        }
+        self.notify(
+            NotificationSeverity::Info,
+            t!(
+                "session.snapshot_restored",
+                time = format_snapshot_time(session.saved_at)
+            ),
+        );
+    }
+
+    /// Close every split but one and every buffer, leaving the editor as a
+    /// session restore at startup finds it
+    fn clear_layout_for_session(&mut self) {
+        if self.is_distraction_free() {
+            self.exit_distraction_free();
+        }
+        if self.split_manager.is_maximized() {
+            let _ = self.split_manager.unmaximize_split();
+        }
+        let active_split = self.split_manager.active_split();
+        for split_id in self.split_manager.root().leaf_split_ids() {
+            if split_id != active_split && self.split_manager.close_split(split_id).is_ok() {
+                self.split_view_states.remove(&split_id);
+            }
+        }
+
+        // Stop the terminals; their backing files stay, since the snapshot
+        // restores terminals from them
+        for (_, terminal_id) in self.terminal_buffers.drain() {
+            self.terminal_manager.close(terminal_id);
+            self.terminal_backing_files.remove(&terminal_id);
+            self.terminal_log_files.remove(&terminal_id);
+        }
+        self.terminal_mode = false;
+
+        // Open the empty buffer first, so closing the others never closes the
+        // last buffer (which would focus the file explorer)
+        let buffer_ids: Vec<BufferId> = self.buffers.keys().copied().collect();
+        self.new_buffer();
+        for buffer_id in buffer_ids {
+            let _ = self.force_close_buffer(buffer_id);
+        }
+        self.bookmarks.clear();
+        self.key_context = KeyContext::Normal;
+    }
+}
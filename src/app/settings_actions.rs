@@ -7,6 +7,7 @@
 //! - Activating/toggling settings
 //! - Incrementing/decrementing numeric values
 
+use crate::config::ConfigError;
 use crate::config_io::{ConfigLayer, ConfigResolver};
 use crate::input::keybindings::KeybindingResolver;
 use rust_i18n::t;
@@ -29,6 +30,12 @@ impl Editor {
                     if let Ok(sources) = resolver.get_layer_sources() {
                         state.set_layer_sources(sources);
                     }
+                    // Settings renamed, retyped or unknown since the file was written
+                    match resolver.read_layer_raw(ConfigLayer::User) {
+                        Ok(Some(user_config)) => state.reconcile_user_config(&user_config),
+                        Ok(None) => {}
+                        Err(e) => tracing::warn!("Failed to read user config: {}", e),
+                    }
                     state.show();
                     self.settings_state = Some(state);
                 }
@@ -60,19 +67,41 @@ impl Editor {
         }
     }
 
+    /// Paste text into the settings input being edited.
+    ///
+    /// Pastes go to the entry dialog if one is open, otherwise to the Text,
+    /// TextList or Map field being edited. Nothing is pasted unless a field is
+    /// in editing mode.
+    pub fn paste_to_settings(&mut self, text: &str) {
+        if text.is_empty() {
+            return;
+        }
+        let Some(state) = self.settings_state.as_mut() else {
+            return;
+        };
+        if let Some(dialog) = state.entry_dialog_mut() {
+            dialog.insert_str(text);
+        } else if state.editing_text {
+            state.text_insert_str(text);
+        }
+    }
+
     /// Save the settings from the modal to config
     pub fn save_settings(&mut self) {
-        let old_theme = self.config.theme.clone();
+        let old_theme = self.configured_theme_name();
+        let old_transparent_background = self.config.ui.transparent_background;
         let old_locale = self.config.locale.clone();
+        let old_todos = self.config.todos.clone();
+        let old_auto_revert = self.config.files.auto_revert;
 
-        // Get target layer and new config
-        let (target_layer, new_config) = {
+        // Get target layer, new config and the user config values to keep as they are
+        let (target_layer, new_config, preserved) = {
             if let Some(ref state) = self.settings_state {
                 if !state.has_changes() {
                     return;
                 }
                 match state.apply_changes(&self.config) {
-                    Ok(config) => (state.target_layer, config),
+                    Ok(config) => (state.target_layer, config, state.preserved_values()),
                     Err(e) => {
                         self.set_status_message(
                             t!("settings.failed_to_apply", error = e.to_string()).to_string(),
@@ -86,12 +115,16 @@ impl Editor {
         };
 
         // Apply the new config
-        self.config = new_config.clone();
+        self.config = new_config;
+        self.preserve_config_values(ConfigLayer::User, preserved);
 
         // Apply runtime changes
-        if old_theme != self.config.theme {
-            self.theme = crate::view::theme::Theme::from_name(&self.config.theme);
-            tracing::info!("Theme changed to '{}'", self.config.theme.0);
+        if old_theme != self.configured_theme_name()
+            || old_transparent_background != self.config.ui.transparent_background
+        {
+            self.theme = self.load_theme(&self.configured_theme_name());
+            tracing::info!("Theme changed to '{}'", self.theme.name);
+            self.theme_changed();
         }
 
         // Apply locale change at runtime
@@ -115,10 +148,18 @@ impl Editor {
 
         // Update keybindings
         self.keybindings = KeybindingResolver::new(&self.config);
+        self.apply_ambiguous_width();
+        self.apply_glyphs();
+        self.apply_clipboard_limits();
+        self.apply_auto_revert_mode(old_auto_revert);
+        self.apply_protected_globs();
+
+        // Redo keyword highlights with the new keywords and colors
+        if old_todos != self.config.todos {
+            self.reset_todo_highlights();
+        }
 
         // Save to disk using the appropriate layer
-        let resolver = ConfigResolver::new(self.dir_context.clone(), self.working_dir.clone());
-
         let layer_name = match target_layer {
             ConfigLayer::User => "User",
             ConfigLayer::Project => "Project",
@@ -126,7 +167,7 @@ impl Editor {
             ConfigLayer::System => "System", // Should never happen
         };
 
-        match resolver.save_to_layer(&new_config, target_layer) {
+        match self.persist_config(target_layer) {
             Ok(()) => {
                 self.set_status_message(
                     t!("settings.saved_to_layer", layer = layer_name).to_string(),
@@ -136,6 +177,11 @@ impl Editor {
                 // settings after save would show stale values.
                 self.settings_state = None;
             }
+            Err(ConfigError::Conflict(_)) => {
+                // The changes are held by the conflict popup; close the modal so
+                // the popup can be answered
+                self.settings_state = None;
+            }
             Err(e) => {
                 self.set_status_message(
                     t!("settings.failed_to_save", error = e.to_string()).to_string(),
@@ -306,7 +352,7 @@ impl Editor {
                     SettingControl::Map(_) => "map",
                     SettingControl::ObjectArray(_) => "objectarray",
                     SettingControl::Json(_) => "json",
-                    SettingControl::Complex { .. } => "complex",
+                    SettingControl::Complex { .. } | SettingControl::ReadOnly { .. } => "complex",
                 })
             } else {
                 None
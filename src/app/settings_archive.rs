@@ -0,0 +1,360 @@
+//! Exporting and importing settings archives
+//!
+//! "Export Settings Archive" opens a checklist popup of the
+//! [`ArchiveCategory`]s, then prompts for the file to write. "Import Settings
+//! Archive" prompts for an archive, reads it and shows what it would change in
+//! a popup; importing backs up the files it replaces first and, when the
+//! config, grammars or plugins changed, offers to restart. See
+//! [`crate::services::settings_archive`].
+
+use std::collections::BTreeSet;
+
+use rust_i18n::t;
+
+use super::Editor;
+use crate::model::event::{PopupContentData, PopupData, PopupListItemData, PopupPositionData};
+use crate::services::settings_archive::{
+    self, ArchiveCategory, FileChange, ImportPlan, SettingsArchive,
+};
+use crate::view::glyphs::glyphs;
+use crate::view::notifications::{
+    next_notification_id, Notification, NotificationAction, NotificationSeverity,
+};
+use crate::view::prompt::PromptType;
+
+/// Popup item data for "Export…"
+const EXPORT_ACTION: &str = "export";
+/// Popup item data for "Import"
+const IMPORT_ACTION: &str = "import";
+/// Popup item data for "Cancel"
+const CANCEL_ACTION: &str = "cancel";
+/// Action id of the "Restart Now" button
+const RESTART_ACTION: &str = "restart";
+
+/// Most replaced files named in the import popup
+const LISTED_REPLACEMENTS: usize = 8;
+
+/// Export choices and an import waiting on its popup
+#[derive(Debug)]
+pub(super) struct SettingsArchiveState {
+    /// Categories checked in the export popup, kept between exports
+    export_categories: BTreeSet<ArchiveCategory>,
+    /// Title of the open export or import popup, used to recognize it
+    popup_title: Option<String>,
+    /// Import shown in the popup
+    pending_import: Option<ImportPlan>,
+    /// Notification offering to restart after an import
+    restart_notification: Option<String>,
+}
+
+impl Default for SettingsArchiveState {
+    fn default() -> Self {
+        Self {
+            export_categories: ArchiveCategory::defaults(),
+            popup_title: None,
+            pending_import: None,
+            restart_notification: None,
+        }
+    }
+}
+
+/// What a category holds, for the checklist
+fn category_label(category: ArchiveCategory) -> String {
+    match category {
+        ArchiveCategory::Config => t!("settings_archive.category_config"),
+        ArchiveCategory::Themes => t!("settings_archive.category_themes"),
+        ArchiveCategory::Grammars => t!("settings_archive.category_grammars"),
+        ArchiveCategory::Plugins => t!("settings_archive.category_plugins"),
+        ArchiveCategory::Layouts => t!("settings_archive.category_layouts"),
+        ArchiveCategory::Sessions => t!("settings_archive.category_sessions"),
+        ArchiveCategory::History => t!("settings_archive.category_history"),
+    }
+    .to_string()
+}
+
+impl Editor {
+    /// Show the export checklist with the item at `selected` highlighted
+    pub fn show_settings_export_popup(&mut self, selected: usize) {
+        let glyphs = glyphs();
+        let state = &self.settings_archive;
+        let mut items: Vec<PopupListItemData> = ArchiveCategory::ALL
+            .into_iter()
+            .map(|category| {
+                let checkbox = if state.export_categories.contains(&category) {
+                    glyphs.checkbox_on
+                } else {
+                    glyphs.checkbox_off
+                };
+                PopupListItemData {
+                    text: format!("{} {}", checkbox, category_label(category)),
+                    detail: (!category.included_by_default())
+                        .then(|| t!("settings_archive.opt_in_detail").to_string()),
+                    icon: None,
+                    data: Some(category.name().to_string()),
+                }
+            })
+            .collect();
+        items.push(PopupListItemData {
+            text: t!("settings_archive.export_action").to_string(),
+            detail: None,
+            icon: None,
+            data: Some(EXPORT_ACTION.to_string()),
+        });
+
+        let title = t!("settings_archive.export_title").to_string();
+        let selected = selected.min(items.len() - 1);
+        let max_height = items.len() as u16 + 4;
+        self.settings_archive.popup_title = Some(title.clone());
+        self.show_popup(PopupData {
+            title: Some(title),
+            description: Some(t!("settings_archive.export_description").to_string()),
+            transient: false,
+            content: PopupContentData::List { items, selected },
+            position: PopupPositionData::Centered,
+            width: 70,
+            max_height,
+            bordered: true,
+        });
+    }
+
+    /// Whether the topmost popup is the export checklist or import preview
+    pub(super) fn is_settings_archive_popup(&self) -> bool {
+        let Some(expected) = &self.settings_archive.popup_title else {
+            return false;
+        };
+        self.active_state()
+            .popups
+            .top()
+            .and_then(|popup| popup.title.as_ref())
+            .is_some_and(|title| title == expected)
+    }
+
+    /// Handle a choice in the export or import popup; `None` means it was
+    /// dismissed
+    pub(super) fn handle_settings_archive_response(&mut self, action: Option<&str>) {
+        self.settings_archive.popup_title = None;
+        let plan = self.settings_archive.pending_import.take();
+        match action {
+            Some(EXPORT_ACTION) => self.start_settings_export_prompt(),
+            Some(IMPORT_ACTION) => {
+                if let Some(plan) = plan {
+                    self.apply_settings_import(&plan);
+                }
+            }
+            Some(CANCEL_ACTION) | None => {}
+            Some(name) => {
+                let Some(category) = ArchiveCategory::from_name(name) else {
+                    return;
+                };
+                let categories = &mut self.settings_archive.export_categories;
+                if !categories.remove(&category) {
+                    categories.insert(category);
+                }
+                let selected = ArchiveCategory::ALL
+                    .iter()
+                    .position(|c| *c == category)
+                    .unwrap_or(0);
+                self.show_settings_export_popup(selected);
+            }
+        }
+    }
+
+    /// Prompt for the file to export the checked categories to
+    fn start_settings_export_prompt(&mut self) {
+        if self.settings_archive.export_categories.is_empty() {
+            self.set_status_message(t!("settings_archive.nothing_selected").to_string());
+            return;
+        }
+        let default = self.working_dir.join(settings_archive::DEFAULT_FILE_NAME);
+        self.start_prompt_with_initial_text(
+            t!("settings_archive.export_prompt").to_string(),
+            PromptType::ExportSettingsArchive,
+            default.to_string_lossy().into_owned(),
+        );
+    }
+
+    /// Write the checked categories to the archive at `input`, relative to
+    /// the working directory
+    pub(super) fn export_settings_archive(&mut self, input: &str) {
+        let input = input.trim();
+        if input.is_empty() {
+            return;
+        }
+        let path = self.working_dir.join(input);
+        let categories = &self.settings_archive.export_categories;
+        match settings_archive::export(&self.dir_context, categories, &path) {
+            Ok(manifest) => self.set_status_message(
+                t!(
+                    "settings_archive.exported",
+                    count = manifest.files.len(),
+                    path = path.display().to_string()
+                )
+                .to_string(),
+            ),
+            Err(e) => {
+                tracing::warn!("Failed to export settings to {:?}: {}", path, e);
+                self.set_status_message(
+                    t!("settings_archive.export_failed", error = e.to_string()).to_string(),
+                )
+            }
+        }
+    }
+
+    /// Prompt for the settings archive to import
+    pub fn start_settings_import_prompt(&mut self) {
+        let default = self.working_dir.join(settings_archive::DEFAULT_FILE_NAME);
+        self.start_prompt_with_initial_text(
+            t!("settings_archive.import_prompt").to_string(),
+            PromptType::ImportSettingsArchive,
+            default.to_string_lossy().into_owned(),
+        );
+    }
+
+    /// Read the archive at `input` and show what importing it would change
+    pub(super) fn preview_settings_import(&mut self, input: &str) {
+        let input = input.trim();
+        if input.is_empty() {
+            return;
+        }
+        let path = self.working_dir.join(input);
+        let plan = SettingsArchive::read(&path).and_then(|archive| {
+            let categories = archive.manifest.categories.iter().copied().collect();
+            archive.plan(&self.dir_context, &categories)
+        });
+        let plan = match plan {
+            Ok(plan) => plan,
+            Err(e) => {
+                tracing::warn!("Failed to read settings archive {:?}: {}", path, e);
+                self.set_status_message(
+                    t!("settings_archive.import_failed", error = e.to_string()).to_string(),
+                );
+                return;
+            }
+        };
+        if plan.is_empty() {
+            self.set_status_message(t!("settings_archive.nothing_to_import").to_string());
+            return;
+        }
+
+        let mut description = t!(
+            "settings_archive.import_summary",
+            new = plan.count(FileChange::New),
+            replaced = plan.count(FileChange::Replace),
+            unchanged = plan.count(FileChange::Unchanged)
+        )
+        .to_string();
+        let replaced: Vec<&str> = plan
+            .files
+            .iter()
+            .filter(|file| file.change == FileChange::Replace)
+            .map(|file| file.archive_path.as_str())
+            .collect();
+        for name in replaced.iter().take(LISTED_REPLACEMENTS) {
+            description.push_str(&format!("\n  {}", name));
+        }
+        if replaced.len() > LISTED_REPLACEMENTS {
+            description.push_str(&format!(
+                "\n  {}",
+                t!(
+                    "settings_archive.more_files",
+                    count = replaced.len() - LISTED_REPLACEMENTS
+                )
+            ));
+        }
+
+        let title = t!("settings_archive.import_title").to_string();
+        self.settings_archive.popup_title = Some(title.clone());
+        self.settings_archive.pending_import = Some(plan);
+        self.show_popup(PopupData {
+            title: Some(title),
+            description: Some(description),
+            transient: false,
+            content: PopupContentData::List {
+                items: vec![
+                    PopupListItemData {
+                        text: t!("settings_archive.import_action").to_string(),
+                        detail: Some(t!("settings_archive.import_action_detail").to_string()),
+                        icon: None,
+                        data: Some(IMPORT_ACTION.to_string()),
+                    },
+                    PopupListItemData {
+                        text: t!("settings_archive.cancel").to_string(),
+                        detail: None,
+                        icon: None,
+                        data: Some(CANCEL_ACTION.to_string()),
+                    },
+                ],
+                selected: 0,
+            },
+            position: PopupPositionData::Centered,
+            width: 70,
+            max_height: 20,
+            bordered: true,
+        });
+    }
+
+    /// Back up and write the files of `plan`, then offer a restart if needed
+    fn apply_settings_import(&mut self, plan: &ImportPlan) {
+        let backup_dir = settings_archive::backup_dir(&self.dir_context);
+        let report = match plan.apply(&backup_dir) {
+            Ok(report) => report,
+            Err(e) => {
+                tracing::warn!("Failed to import settings: {}", e);
+                self.set_status_message(
+                    t!("settings_archive.import_failed", error = e.to_string()).to_string(),
+                );
+                return;
+            }
+        };
+        let message = if report.backed_up > 0 {
+            t!(
+                "settings_archive.imported_backup",
+                count = report.written,
+                path = backup_dir.display().to_string()
+            )
+        } else {
+            t!("settings_archive.imported", count = report.written)
+        };
+        self.set_status_message(message.to_string());
+
+        if plan.needs_restart() {
+            let id = next_notification_id();
+            self.post_notification(
+                Notification::new(
+                    id.clone(),
+                    NotificationSeverity::Info,
+                    t!("settings_archive.restart").to_string(),
+                )
+                .with_actions(vec![NotificationAction {
+                    id: RESTART_ACTION.to_string(),
+                    label: t!("settings_archive.restart_now").to_string(),
+                }])
+                .with_timeout(None),
+            );
+            self.settings_archive.restart_notification = Some(id);
+        }
+    }
+
+    /// Handle the answer to the "restart after import" notification
+    ///
+    /// Returns false if the notification wasn't about an import.
+    pub(super) fn handle_settings_import_answer(
+        &mut self,
+        notification_id: &str,
+        action: Option<&str>,
+    ) -> bool {
+        if self.settings_archive.restart_notification.as_deref() != Some(notification_id) {
+            return false;
+        }
+        self.settings_archive.restart_notification = None;
+        if action == Some(RESTART_ACTION) {
+            let modified = self.count_modified_buffers();
+            if modified > 0 {
+                self.set_status_message(t!("update.restart_unsaved", count = modified).to_string());
+            } else {
+                self.request_restart(self.working_dir.clone());
+            }
+        }
+        true
+    }
+}
@@ -5,6 +5,7 @@
 //! - Output results to a new buffer or replace the input content
 
 use std::io::Write;
+use std::path::Path;
 use std::process::{Command, Stdio};
 
 use super::Editor;
@@ -30,49 +31,7 @@ impl Editor {
     pub fn execute_shell_command(&mut self, command: &str) -> Result<String, String> {
         // Get the input text (selection or entire buffer)
         let input = self.get_shell_input();
-
-        // Detect the shell to use
-        let shell = detect_shell();
-
-        // Execute the command
-        let mut child = Command::new(&shell)
-            .args(["-c", command])
-            .stdin(Stdio::piped())
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .spawn()
-            .map_err(|e| format!("Failed to spawn shell: {}", e))?;
-
-        // Write input to stdin
-        if let Some(mut stdin) = child.stdin.take() {
-            stdin
-                .write_all(input.as_bytes())
-                .map_err(|e| format!("Failed to write to stdin: {}", e))?;
-        }
-
-        // Wait for the command to complete
-        let output = child
-            .wait_with_output()
-            .map_err(|e| format!("Failed to wait for command: {}", e))?;
-
-        if output.status.success() {
-            String::from_utf8(output.stdout).map_err(|e| format!("Invalid UTF-8 in output: {}", e))
-        } else {
-            // Include stderr in error message
-            let stderr = String::from_utf8_lossy(&output.stderr);
-            let stdout = String::from_utf8_lossy(&output.stdout);
-            if !stderr.is_empty() {
-                Err(format!("Command failed: {}", stderr.trim()))
-            } else if !stdout.is_empty() {
-                // Some commands output errors to stdout
-                Err(format!("Command failed: {}", stdout.trim()))
-            } else {
-                Err(format!(
-                    "Command failed with exit code: {:?}",
-                    output.status.code()
-                ))
-            }
-        }
+        run_shell_command(command, None, Some(&input))
     }
 
     /// Get the input for shell command (selection or entire buffer).
@@ -121,7 +80,7 @@ impl Editor {
         match self.execute_shell_command(command) {
             Ok(output) => {
                 if replace {
-                    self.replace_with_shell_output(&output, has_selection, selection_info);
+                    self.replace_with_shell_output(command, &output, has_selection, selection_info);
                 } else {
                     self.create_shell_output_buffer(command, &output);
                 }
@@ -132,13 +91,15 @@ impl Editor {
         }
     }
 
-    /// Replace the current selection or buffer with shell output.
+    /// Replace the current selection or buffer with the output of `command`.
     fn replace_with_shell_output(
         &mut self,
+        command: &str,
         output: &str,
         has_selection: bool,
         selection_info: Option<(usize, usize, String)>,
     ) {
+        let label = t!("undo.shell_command_replace", command = command).to_string();
         let cursor_id = self.active_state().cursors.primary_id();
 
         // Capture cursor position and selection state before replacement
@@ -168,7 +129,8 @@ impl Editor {
                     events: vec![delete_event, insert_event],
                     description: "Shell command replace".to_string(),
                 };
-                self.active_event_log_mut().append(batch.clone());
+                self.active_event_log_mut()
+                    .append_labeled(batch.clone(), label);
                 self.apply_event_to_active_buffer(&batch);
             }
         } else {
@@ -213,7 +175,8 @@ impl Editor {
                 events,
                 description: "Shell command replace buffer".to_string(),
             };
-            self.active_event_log_mut().append(batch.clone());
+            self.active_event_log_mut()
+                .append_labeled(batch.clone(), label);
             self.apply_event_to_active_buffer(&batch);
         }
 
@@ -247,6 +210,75 @@ impl Editor {
     }
 }
 
+/// Run `command` in the user's shell, in `dir` (or the editor's own
+/// directory), with `input` as stdin
+///
+/// Returns its output, or a message with its error output if it failed.
+pub(crate) fn run_shell_command(
+    command: &str,
+    dir: Option<&Path>,
+    input: Option<&str>,
+) -> Result<String, String> {
+    let mut shell = Command::new(detect_shell());
+    shell
+        .args(["-c", command])
+        .stdin(if input.is_some() {
+            Stdio::piped()
+        } else {
+            Stdio::null()
+        })
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    if let Some(dir) = dir {
+        shell.current_dir(dir);
+    }
+    let mut child = shell
+        .spawn()
+        .map_err(|e| t!("shell.spawn_failed", error = e.to_string()).to_string())?;
+
+    // Write input from another thread, so a command that prints a lot
+    // before it has read all of it can't block on a full pipe
+    let writer = match (child.stdin.take(), input) {
+        (Some(mut stdin), Some(input)) => {
+            let input = input.to_string();
+            Some(std::thread::spawn(move || {
+                stdin.write_all(input.as_bytes())
+            }))
+        }
+        _ => None,
+    };
+
+    // Wait for the command to complete
+    let output = child
+        .wait_with_output()
+        .map_err(|e| t!("shell.wait_failed", error = e.to_string()).to_string())?;
+    if let Some(writer) = writer {
+        // A command that exits without reading all of its input is fine
+        let _ = writer.join();
+    }
+
+    if output.status.success() {
+        String::from_utf8(output.stdout)
+            .map_err(|e| t!("shell.invalid_utf8", error = e.to_string()).to_string())
+    } else {
+        // Include stderr in error message
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        if !stderr.is_empty() {
+            Err(t!("shell.command_failed", error = stderr.trim()).to_string())
+        } else if !stdout.is_empty() {
+            // Some commands output errors to stdout
+            Err(t!("shell.command_failed", error = stdout.trim()).to_string())
+        } else {
+            Err(t!(
+                "shell.exit_code",
+                code = format!("{:?}", output.status.code())
+            )
+            .to_string())
+        }
+    }
+}
+
 /// Detect the shell to use for executing commands.
 fn detect_shell() -> String {
     // Try SHELL environment variable first
@@ -0,0 +1,128 @@
+//! Plugin hooks for changes to the split layout
+//!
+//! The split manager records every change made to the split tree; they are
+//! turned into `split_created`, `split_closed`, `split_focused` and
+//! `split_resized` hooks here, with `by_plugin` set for the changes a plugin
+//! command made. Resizes while a separator is being dragged are held back
+//! and fire once the drag ends. Restoring a session, a session snapshot or a
+//! named layout fires a single `layout_restored` instead of the changes that
+//! rebuilt the layout.
+
+use super::Editor;
+use crate::model::event::{SplitDirection, SplitId};
+use crate::services::plugins::hooks::HookArgs;
+use crate::view::split::SplitChange;
+
+/// Split changes held back from plugins
+#[derive(Debug, Default)]
+pub(super) struct SplitHookState {
+    /// Last ratio of each container resized by the separator drag going on
+    dragged: Vec<(SplitId, f32)>,
+    /// Layout restores going on; their changes aren't reported one by one
+    restoring: usize,
+}
+
+fn direction_name(direction: SplitDirection) -> &'static str {
+    match direction {
+        SplitDirection::Horizontal => "horizontal",
+        SplitDirection::Vertical => "vertical",
+    }
+}
+
+impl Editor {
+    /// Run the hooks of the split changes made since the last call
+    pub(super) fn flush_split_changes(&mut self, by_plugin: bool) {
+        let changes = self.split_manager.take_changes();
+        if self.split_hooks.restoring > 0 {
+            return;
+        }
+        let dragging = self.mouse_state.dragging_separator.is_some();
+        for change in changes {
+            match change {
+                SplitChange::Resized { split_id, ratio } if dragging => {
+                    let dragged = &mut self.split_hooks.dragged;
+                    match dragged.iter_mut().find(|(id, _)| *id == split_id) {
+                        Some(entry) => entry.1 = ratio,
+                        None => dragged.push((split_id, ratio)),
+                    }
+                }
+                change => self.run_split_hook(change, by_plugin),
+            }
+        }
+        if !dragging {
+            for (split_id, ratio) in std::mem::take(&mut self.split_hooks.dragged) {
+                self.run_split_hook(SplitChange::Resized { split_id, ratio }, false);
+            }
+        }
+    }
+
+    fn run_split_hook(&mut self, change: SplitChange, by_plugin: bool) {
+        let (name, args) = match change {
+            SplitChange::Created {
+                split_id,
+                parent_id,
+                direction,
+            } => (
+                "split_created",
+                HookArgs::SplitCreated {
+                    split_id,
+                    parent_id,
+                    direction: direction_name(direction).to_string(),
+                    by_plugin,
+                },
+            ),
+            SplitChange::Closed { split_id } => (
+                "split_closed",
+                HookArgs::SplitClosed {
+                    split_id,
+                    by_plugin,
+                },
+            ),
+            SplitChange::Focused {
+                split_id,
+                previous_split_id,
+            } => (
+                "split_focused",
+                HookArgs::SplitFocused {
+                    split_id,
+                    previous_split_id,
+                    by_plugin,
+                },
+            ),
+            SplitChange::Resized { split_id, ratio } => (
+                "split_resized",
+                HookArgs::SplitResized {
+                    split_id,
+                    ratio,
+                    by_plugin,
+                },
+            ),
+        };
+        self.plugin_manager.run_hook(name, args);
+    }
+
+    /// Start rebuilding the layout from saved state; the changes made until
+    /// [`Self::finish_layout_restore`] aren't reported one by one
+    pub(super) fn begin_layout_restore(&mut self) {
+        self.flush_split_changes(false);
+        self.split_hooks.restoring += 1;
+    }
+
+    /// Finish a layout restore, firing `layout_restored` if it was applied
+    /// or changed the layout on the way
+    ///
+    /// `source` says what was restored: "session", "snapshot" or "layout".
+    pub(super) fn finish_layout_restore(&mut self, source: &str, applied: bool) {
+        let changed = !self.split_manager.take_changes().is_empty();
+        self.split_hooks.restoring = self.split_hooks.restoring.saturating_sub(1);
+        if self.split_hooks.restoring == 0 && (applied || changed) {
+            self.plugin_manager.run_hook(
+                "layout_restored",
+                HookArgs::LayoutRestored {
+                    source: source.to_string(),
+                    active_split_id: self.split_manager.active_split(),
+                },
+            );
+        }
+    }
+}
@@ -0,0 +1,61 @@
+//! Input lock of a split ("Toggle Pane Lock")
+//!
+//! A locked split rejects edits typed while it has focus: typing, deleting,
+//! pasting, undo and the other buffer-changing actions are stopped with a
+//! status message before they run, while moving, selecting and copying still
+//! work. The lock belongs to the split, not the buffer, so the same buffer
+//! stays editable from another split, and edits plugins make to the buffer
+//! directly are not affected.
+
+use rust_i18n::t;
+
+use crate::input::keybindings::{Action, KeyContext};
+use crate::model::event::SplitId;
+
+use super::Editor;
+
+impl Editor {
+    /// Whether input to `split_id` is locked
+    pub fn is_split_locked(&self, split_id: SplitId) -> bool {
+        self.split_view_states
+            .get(&split_id)
+            .is_some_and(|view_state| view_state.input_locked)
+    }
+
+    /// Lock or unlock input to a split
+    pub fn set_split_locked(&mut self, split_id: SplitId, locked: bool) {
+        match self.split_view_states.get_mut(&split_id) {
+            Some(view_state) => view_state.input_locked = locked,
+            None => tracing::warn!("Cannot lock unknown split {:?}", split_id),
+        }
+    }
+
+    /// Lock or unlock the focused split
+    pub(super) fn toggle_split_lock(&mut self) {
+        let split_id = self.split_manager.active_split();
+        let locked = !self.is_split_locked(split_id);
+        self.set_split_locked(split_id, locked);
+        self.set_status_message(if locked {
+            t!("split.locked").to_string()
+        } else {
+            t!("split.unlocked").to_string()
+        });
+    }
+
+    /// Check whether the focused split's lock stops an edit, saying so in
+    /// the status bar if it does
+    ///
+    /// Only edits aimed at the buffer count; typing into a prompt or the
+    /// file explorer goes through.
+    pub(super) fn split_lock_blocks(&mut self, action: &Action) -> bool {
+        if !action.edits_buffer()
+            || self.is_prompting()
+            || self.key_context != KeyContext::Normal
+            || !self.is_split_locked(self.split_manager.active_split())
+        {
+            return false;
+        }
+        self.set_status_message(t!("split.locked_edit").to_string());
+        true
+    }
+}
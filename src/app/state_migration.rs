@@ -0,0 +1,149 @@
+//! Moving state out of the legacy data directory
+//!
+//! Sessions, recovery files and histories used to live in the data directory
+//! (~/.local/share/fresh on Linux) and now live in the state directory. When
+//! the old directory still holds some, a popup offers to move them once; see
+//! [`StateMigration`].
+
+use rust_i18n::t;
+
+use super::Editor;
+use crate::config_io::StateMigration;
+
+/// Popup item data for "Move"
+const MOVE_ACTION: &str = "move";
+/// Popup item data for "Keep"
+const KEEP_ACTION: &str = "keep";
+
+/// A migration waiting on the popup
+#[derive(Debug)]
+pub(super) struct PendingStateMigration {
+    migration: StateMigration,
+    /// Title of the popup, used to recognize it
+    popup_title: String,
+}
+
+impl Editor {
+    /// Offer to move state left in the legacy state directory, if there is any
+    pub fn offer_state_migration(&mut self) {
+        use crate::model::event::{
+            PopupContentData, PopupData, PopupListItemData, PopupPositionData,
+        };
+
+        let Some(migration) = self.dir_context.pending_state_migration() else {
+            return;
+        };
+        let title = t!("migration.title").to_string();
+
+        let popup = PopupData {
+            title: Some(title.clone()),
+            description: Some(
+                t!(
+                    "migration.description",
+                    from = migration.from.display().to_string(),
+                    to = migration.to.display().to_string()
+                )
+                .to_string(),
+            ),
+            transient: false,
+            content: PopupContentData::List {
+                items: vec![
+                    PopupListItemData {
+                        text: t!("migration.move").to_string(),
+                        detail: Some(t!("migration.move_detail").to_string()),
+                        icon: None,
+                        data: Some(MOVE_ACTION.to_string()),
+                    },
+                    PopupListItemData {
+                        text: t!("migration.keep").to_string(),
+                        detail: Some(t!("migration.keep_detail").to_string()),
+                        icon: None,
+                        data: Some(KEEP_ACTION.to_string()),
+                    },
+                ],
+                selected: 0,
+            },
+            position: PopupPositionData::Centered,
+            width: 70,
+            max_height: 8,
+            bordered: true,
+        };
+
+        self.pending_state_migration = Some(PendingStateMigration {
+            migration,
+            popup_title: title,
+        });
+        self.show_popup(popup);
+    }
+
+    /// Whether the topmost popup is the state migration popup
+    pub(super) fn is_state_migration_popup(&self) -> bool {
+        let Some(pending) = &self.pending_state_migration else {
+            return false;
+        };
+        self.active_state()
+            .popups
+            .top()
+            .and_then(|popup| popup.title.as_ref())
+            .is_some_and(|title| *title == pending.popup_title)
+    }
+
+    /// Handle the user's choice in the state migration popup.
+    ///
+    /// `None` means the popup was dismissed: nothing is moved and the user is
+    /// asked again next time.
+    pub(super) fn handle_state_migration_response(&mut self, action: Option<&str>) {
+        let Some(pending) = self.pending_state_migration.take() else {
+            return;
+        };
+        let migration = pending.migration;
+
+        match action {
+            Some(MOVE_ACTION) => match migration.run() {
+                Ok(report) if report.skipped.is_empty() => self.set_status_message(
+                    t!(
+                        "migration.moved",
+                        count = report.moved,
+                        path = migration.to.display().to_string()
+                    )
+                    .to_string(),
+                ),
+                Ok(report) => {
+                    tracing::warn!(
+                        "Left in {:?} because {:?} already has them: {:?}",
+                        migration.from,
+                        migration.to,
+                        report.skipped
+                    );
+                    self.set_status_message(
+                        t!(
+                            "migration.moved_some",
+                            count = report.moved,
+                            skipped = report.skipped.len(),
+                            path = migration.from.display().to_string()
+                        )
+                        .to_string(),
+                    )
+                }
+                Err(e) => {
+                    tracing::warn!("Failed to move state from {:?}: {}", migration.from, e);
+                    self.set_status_message(
+                        t!("migration.failed", error = e.to_string()).to_string(),
+                    )
+                }
+            },
+            Some(KEEP_ACTION) => match migration.dismiss() {
+                Ok(()) => self.set_status_message(
+                    t!(
+                        "migration.kept",
+                        path = migration.from.display().to_string()
+                    )
+                    .to_string(),
+                ),
+                Err(e) => self
+                    .set_status_message(t!("migration.failed", error = e.to_string()).to_string()),
+            },
+            _ => self.set_status_message(t!("migration.postponed").to_string()),
+        }
+    }
+}
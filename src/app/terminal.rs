@@ -337,13 +337,17 @@ impl Editor {
 
         // Get visible buffers with their areas
         let visible_buffers = self.split_manager.get_visible_buffers(editor_area);
+        let winbar_rows = u16::from(
+            self.distraction_free.is_none() && self.config.ui.winbar.shows(visible_buffers.len()),
+        );
 
         // Resize each terminal buffer to match its split content area
         for (_split_id, buffer_id, split_area) in visible_buffers {
             if self.terminal_buffers.contains_key(&buffer_id) {
                 // Calculate content dimensions (accounting for tab bar and borders)
-                // Tab bar takes 1 row, and we leave 1 for scrollbar width on right
-                let content_height = split_area.height.saturating_sub(2);
+                // Tab bar takes 1 row, and we leave 1 for scrollbar width on right;
+                // a winbar takes one more row
+                let content_height = split_area.height.saturating_sub(2 + winbar_rows);
                 let content_width = split_area.width.saturating_sub(2);
 
                 if content_width > 0 && content_height > 0 {
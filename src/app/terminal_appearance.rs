@@ -0,0 +1,148 @@
+//! Following the terminal between light and dark
+//!
+//! With `theme = "auto"` the editor shows `ui.light_theme` on a light
+//! terminal background and `ui.dark_theme` on a dark one. It asks the
+//! terminal for its background color with OSC 11 at startup and whenever
+//! the terminal gains focus, so a switch made by the OS while the editor was
+//! in the background shows on return. The reply comes in with the key
+//! presses and is picked out of them by [`ReplyReader`]; nothing waits for
+//! it. The notification kitty and foot send on a switch (`CSI ? 997 ; n n`)
+//! is dropped by crossterm, so the editor can't listen for it.
+//!
+//! "Toggle Light/Dark Theme" switches to the other of the two themes the
+//! same way, until the terminal's background changes again.
+
+use crossterm::event::KeyEvent;
+use rust_i18n::t;
+
+use super::Editor;
+use crate::config::{Config, UiConfig};
+use crate::input::terminal_reply::{ReadKey, ReplyReader};
+use crate::view::terminal_effects::TerminalEffect;
+use crate::view::theme::Appearance;
+
+/// Which of the light and dark themes to show
+#[derive(Debug)]
+pub(crate) struct TerminalAppearance {
+    /// The appearance shown with `theme = "auto"`, and toggled from
+    shown: Appearance,
+    /// The background the terminal reported last
+    reported: Option<Appearance>,
+    reader: ReplyReader,
+}
+
+impl Default for TerminalAppearance {
+    fn default() -> Self {
+        Self {
+            shown: Appearance::Dark,
+            reported: None,
+            reader: ReplyReader::default(),
+        }
+    }
+}
+
+/// The theme of `ui.light_theme` or `ui.dark_theme` for `appearance`
+fn light_dark_theme_name(ui: &UiConfig, appearance: Appearance) -> &str {
+    match appearance {
+        Appearance::Light => ui.light_theme.as_deref().unwrap_or("light"),
+        Appearance::Dark => ui.dark_theme.as_deref().unwrap_or("dark"),
+    }
+}
+
+/// The theme `config` asks for, for "auto" the one for `appearance`
+pub(crate) fn configured_theme_name(config: &Config, appearance: Appearance) -> &str {
+    if config.theme.is_auto() {
+        light_dark_theme_name(&config.ui, appearance)
+    } else {
+        &config.theme
+    }
+}
+
+impl Editor {
+    /// The theme the config asks for, for "auto" the light or dark one shown
+    pub(super) fn configured_theme_name(&self) -> String {
+        configured_theme_name(&self.config, self.terminal_appearance.shown).to_string()
+    }
+
+    /// Ask the terminal for its background color
+    ///
+    /// The reply comes in with the key presses, which go through
+    /// [`Self::sift_terminal_reply`].
+    pub fn query_terminal_background(&mut self) {
+        self.terminal_effects.push(TerminalEffect::QueryBackground);
+        self.terminal_appearance
+            .reader
+            .expect_reply(self.time_source.now());
+    }
+
+    /// The keys to handle for a key press: none while it's part of a
+    /// background color reply, which is followed once complete
+    pub fn sift_terminal_reply(&mut self, key: KeyEvent) -> Vec<KeyEvent> {
+        let now = self.time_source.now();
+        match self.terminal_appearance.reader.read_key(key, now) {
+            ReadKey::Keys(keys) => keys,
+            ReadKey::Held => Vec::new(),
+            ReadKey::Reply(appearance) => {
+                match appearance {
+                    Some(appearance) => {
+                        self.handle_terminal_background(appearance);
+                    }
+                    None => tracing::debug!("Unreadable terminal background color reply"),
+                }
+                Vec::new()
+            }
+        }
+    }
+
+    /// Keys held for a background color reply that didn't arrive in time,
+    /// to handle as typed
+    pub fn check_terminal_reply_timer(&mut self) -> Vec<KeyEvent> {
+        let now = self.time_source.now();
+        self.terminal_appearance.reader.take_overdue(now)
+    }
+
+    /// Follow the terminal's background: with `theme = "auto"`, a change of
+    /// it switches to the light or dark theme
+    ///
+    /// Returns true if the theme switched.
+    pub fn handle_terminal_background(&mut self, appearance: Appearance) -> bool {
+        if self.terminal_appearance.reported == Some(appearance) {
+            return false;
+        }
+        tracing::debug!("Terminal background is {:?}", appearance);
+        self.terminal_appearance.reported = Some(appearance);
+        if !self.config.theme.is_auto() {
+            // Kept for a later switch to "auto"
+            self.terminal_appearance.shown = appearance;
+            return false;
+        }
+        if appearance == self.terminal_appearance.shown {
+            return false;
+        }
+        self.switch_appearance(appearance);
+        true
+    }
+
+    /// Switch between the light and dark themes
+    pub(super) fn toggle_light_dark_theme(&mut self) {
+        let current = if self.config.theme.is_auto() {
+            self.terminal_appearance.shown
+        } else {
+            Appearance::of_background(self.theme.editor_bg)
+                .unwrap_or(self.terminal_appearance.shown)
+        };
+        self.switch_appearance(current.toggled());
+    }
+
+    /// Show the light or dark theme live
+    fn switch_appearance(&mut self, appearance: Appearance) {
+        self.terminal_appearance.shown = appearance;
+        let name = light_dark_theme_name(&self.config.ui, appearance).to_string();
+        self.theme = self.load_theme(&name);
+        self.apply_glyphs();
+        self.theme_changed();
+        self.set_status_message(
+            t!("view.theme_changed", theme = self.theme.name.clone()).to_string(),
+        );
+    }
+}
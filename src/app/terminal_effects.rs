@@ -0,0 +1,27 @@
+//! Escape sequences the editor sends outside the drawn frame
+//!
+//! Editor code queues them in `terminal_effects`; the cursor style and OSC 52
+//! copies are picked up when the event loop asks for the frame's effects.
+//! See [`crate::view::terminal_effects`].
+
+use std::time::Instant;
+
+use crate::view::terminal_effects::TerminalEffect;
+
+use super::Editor;
+
+impl Editor {
+    /// Escape sequences to write before drawing the next frame, in order,
+    /// each kind at most once
+    pub fn take_terminal_effects(&mut self, now: Instant) -> Vec<TerminalEffect> {
+        if let Some(style) = self.take_cursor_style_change(now) {
+            tracing::debug!("Set cursor style to {:?}", style);
+            self.terminal_effects
+                .push(TerminalEffect::SetCursorStyle(style));
+        }
+        if let Some(text) = self.clipboard.take_osc52_copy() {
+            self.terminal_effects.push(TerminalEffect::Osc52Copy(text));
+        }
+        self.terminal_effects.take()
+    }
+}
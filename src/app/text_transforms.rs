@@ -0,0 +1,304 @@
+//! Sort, unique, reverse, shuffle and case conversion commands
+//!
+//! Line transforms ("Sort Lines", "Unique Lines", "Reverse Lines", "Shuffle
+//! Lines") rearrange the whole lines each selection touches; case
+//! conversions (Title Case, camelCase, snake_case) re-case exactly the
+//! selected text. Each selection is transformed on its own, except that
+//! selections touching the same lines are transformed together. With
+//! nothing selected the command asks before transforming the whole buffer.
+//!
+//! All cursors are edited in one undo step, and each transformed region is
+//! left selected. The text itself is transformed by
+//! [`crate::primitives::text_transform`].
+
+use std::ops::Range;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use super::Editor;
+use crate::input::keybindings::Action;
+use crate::model::buffer::Buffer;
+use crate::model::cursor::Cursor;
+use crate::model::event::{CursorId, Event};
+use crate::primitives::text_transform::{CaseStyle, SortOptions, TextTransform};
+use crate::view::prompt::PromptType;
+use rust_i18n::t;
+
+/// Text transformed as one piece, and the cursors whose selections it covers
+struct TransformRegion {
+    range: Range<usize>,
+    cursors: Vec<(CursorId, Cursor)>,
+}
+
+impl Editor {
+    /// Run one of the text transform actions
+    pub(super) fn handle_text_transform_action(&mut self, action: Action) {
+        if self.edit_blocked(&action) {
+            return;
+        }
+        let transform = match action {
+            Action::SortLines => {
+                self.start_sort_lines_prompt();
+                return;
+            }
+            Action::UniqueLines => TextTransform::UniqueLines {
+                adjacent_only: false,
+            },
+            Action::UniqueAdjacentLines => TextTransform::UniqueLines {
+                adjacent_only: true,
+            },
+            Action::ReverseLines => TextTransform::ReverseLines,
+            Action::ShuffleLines => TextTransform::ShuffleLines,
+            Action::ToTitleCase => TextTransform::Case(CaseStyle::Title),
+            Action::ToCamelCase => TextTransform::Case(CaseStyle::Camel),
+            Action::ToSnakeCase => TextTransform::Case(CaseStyle::Snake),
+            _ => return,
+        };
+        self.transform_text(transform);
+    }
+
+    /// Ask for the "Sort Lines" options, then sort
+    pub fn start_sort_lines_prompt(&mut self) {
+        self.start_prompt(
+            t!("transform.sort_prompt").to_string(),
+            PromptType::SortLines,
+        );
+    }
+
+    /// Sort with the options typed in the "Sort Lines" prompt
+    pub(super) fn handle_sort_lines_prompt(&mut self, input: &str) {
+        match SortOptions::parse(input) {
+            Some(options) => self.transform_text(TextTransform::SortLines(options)),
+            None => self.set_status_message(
+                t!("transform.sort_invalid_options", options = input.trim()).to_string(),
+            ),
+        }
+    }
+
+    /// Apply `transform` to the selections, or to the whole buffer after
+    /// confirming when nothing is selected
+    pub fn transform_text(&mut self, transform: TextTransform) {
+        let has_selection = self
+            .active_state()
+            .cursors
+            .iter()
+            .any(|(_, cursor)| cursor.selection_range().is_some());
+        if has_selection {
+            self.apply_text_transform(transform, false);
+        } else {
+            self.start_prompt(
+                t!(
+                    "transform.confirm_whole_buffer",
+                    command = transform_name(transform)
+                )
+                .to_string(),
+                PromptType::ConfirmTransformBuffer { transform },
+            );
+        }
+    }
+
+    /// Answer to "transform the whole buffer?"
+    pub(super) fn handle_confirm_transform_buffer(
+        &mut self,
+        input: &str,
+        transform: TextTransform,
+    ) {
+        let input_lower = input.trim().to_lowercase();
+        if input_lower == "a" || input_lower == "apply" {
+            self.apply_text_transform(transform, true);
+        } else {
+            self.set_status_message(t!("transform.cancelled").to_string());
+        }
+    }
+
+    fn apply_text_transform(&mut self, transform: TextTransform, whole_buffer: bool) {
+        let regions = self.transform_regions(transform.works_on_lines(), whole_buffer);
+        let seed = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_nanos() as u64);
+        let state = self.active_state_mut();
+        let line_ending = state.buffer.line_ending().as_str();
+
+        // New text of each region, in buffer order
+        let mut replacements = Vec::with_capacity(regions.len());
+        for region in &regions {
+            let text = state.get_text_range(region.range.start, region.range.end);
+            let transformed = transform.apply(&text, line_ending, seed);
+            replacements.push((text, transformed));
+        }
+        if replacements
+            .iter()
+            .all(|(text, transformed)| text == transformed)
+        {
+            self.set_status_message(t!("transform.unchanged").to_string());
+            return;
+        }
+
+        let mut events = Vec::new();
+        // From the end, so earlier edits don't move later positions
+        for (region, (text, transformed)) in regions.iter().zip(&replacements).rev() {
+            if text == transformed {
+                continue;
+            }
+            let cursor_id = region.cursors[0].0;
+            events.push(Event::Delete {
+                range: region.range.clone(),
+                deleted_text: text.clone(),
+                cursor_id,
+            });
+            events.push(Event::Insert {
+                position: region.range.start,
+                text: transformed.clone(),
+                cursor_id,
+            });
+        }
+
+        // Select each region where it ends up
+        let mut shift = 0isize;
+        for (region, (text, transformed)) in regions.iter().zip(&replacements) {
+            let start = (region.range.start as isize + shift) as usize;
+            let end = start + transformed.len();
+            shift += transformed.len() as isize - text.len() as isize;
+            for (cursor_id, cursor) in &region.cursors {
+                events.push(Event::MoveCursor {
+                    cursor_id: *cursor_id,
+                    old_position: cursor.position,
+                    new_position: end,
+                    old_anchor: cursor.anchor,
+                    new_anchor: Some(start),
+                    old_sticky_column: cursor.sticky_column,
+                    new_sticky_column: 0,
+                });
+            }
+        }
+        if whole_buffer {
+            // Other cursors stay where they were, within the new text
+            let state = self.active_state();
+            let new_len = (state.buffer.len() as isize + shift) as usize;
+            let primary_id = state.cursors.primary_id();
+            for (cursor_id, cursor) in state.cursors.iter() {
+                if cursor_id != primary_id {
+                    events.push(Event::MoveCursor {
+                        cursor_id,
+                        old_position: cursor.position,
+                        new_position: cursor.position.min(new_len),
+                        old_anchor: cursor.anchor,
+                        new_anchor: None,
+                        old_sticky_column: cursor.sticky_column,
+                        new_sticky_column: cursor.sticky_column,
+                    });
+                }
+            }
+        }
+
+        if let Some(bulk_edit) = self.apply_events_as_bulk_edit(events, transform_name(transform)) {
+            let label = match transform {
+                TextTransform::SortLines(_) => {
+                    let count: usize = replacements
+                        .iter()
+                        .map(|(text, _)| text.lines().count())
+                        .sum();
+                    t!("undo.sort_lines", count = count).to_string()
+                }
+                _ => transform_name(transform),
+            };
+            self.active_event_log_mut().append_labeled(bulk_edit, label);
+        }
+    }
+
+    /// The regions to transform, in buffer order
+    ///
+    /// With `whole_buffer` that is all of the buffer, selected by the primary
+    /// cursor; otherwise each selection, widened to whole lines when
+    /// `whole_lines` is set.
+    fn transform_regions(&mut self, whole_lines: bool, whole_buffer: bool) -> Vec<TransformRegion> {
+        let state = self.active_state_mut();
+        if whole_buffer {
+            let primary_id = state.cursors.primary_id();
+            let primary = *state.cursors.primary();
+            let mut range = 0..state.buffer.len();
+            if whole_lines {
+                range = line_span(&mut state.buffer, range);
+            }
+            return vec![TransformRegion {
+                range,
+                cursors: vec![(primary_id, primary)],
+            }];
+        }
+
+        let mut selections: Vec<(Range<usize>, CursorId, Cursor)> = state
+            .cursors
+            .iter()
+            .filter_map(|(cursor_id, cursor)| {
+                cursor
+                    .selection_range()
+                    .map(|range| (range, cursor_id, *cursor))
+            })
+            .collect();
+        selections.sort_by_key(|(range, _, _)| range.start);
+
+        let mut regions: Vec<TransformRegion> = Vec::new();
+        for (range, cursor_id, cursor) in selections {
+            let range = if whole_lines {
+                line_span(&mut state.buffer, range)
+            } else {
+                range
+            };
+            match regions.last_mut() {
+                // Selections on the same lines are transformed together
+                Some(last) if range.start <= last.range.end => {
+                    last.range.end = last.range.end.max(range.end);
+                    last.cursors.push((cursor_id, cursor));
+                }
+                _ => regions.push(TransformRegion {
+                    range,
+                    cursors: vec![(cursor_id, cursor)],
+                }),
+            }
+        }
+        regions
+    }
+}
+
+/// The lines `range` touches, without the last one's line ending
+///
+/// A range ending at the start of a line doesn't include that line, so a
+/// selection made by selecting whole lines covers just those lines.
+fn line_span(buffer: &mut Buffer, range: Range<usize>) -> Range<usize> {
+    let start = buffer
+        .line_iterator(range.start, 80)
+        .next()
+        .map_or(range.start, |(line_start, _)| line_start);
+    let ends_at_line_start =
+        range.end > range.start && buffer.slice_bytes(range.end - 1..range.end) == b"\n";
+    let last = if ends_at_line_start {
+        range.end - 1
+    } else {
+        range.end
+    };
+    let end = buffer
+        .line_iterator(last, 80)
+        .next()
+        .map_or(last, |(line_start, line)| {
+            line_start + line.trim_end_matches(['\n', '\r']).len()
+        });
+    start..end.max(start)
+}
+
+/// Name of the command doing `transform`, for prompts and undo
+fn transform_name(transform: TextTransform) -> String {
+    match transform {
+        TextTransform::SortLines(_) => t!("action.sort_lines"),
+        TextTransform::UniqueLines {
+            adjacent_only: false,
+        } => t!("action.unique_lines"),
+        TextTransform::UniqueLines {
+            adjacent_only: true,
+        } => t!("action.unique_adjacent_lines"),
+        TextTransform::ReverseLines => t!("action.reverse_lines"),
+        TextTransform::ShuffleLines => t!("action.shuffle_lines"),
+        TextTransform::Case(CaseStyle::Title) => t!("action.to_title_case"),
+        TextTransform::Case(CaseStyle::Camel) => t!("action.to_camel_case"),
+        TextTransform::Case(CaseStyle::Snake) => t!("action.to_snake_case"),
+    }
+    .to_string()
+}
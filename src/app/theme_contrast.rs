@@ -0,0 +1,123 @@
+//! "Check Theme Contrast": the contrast of the active theme's color pairs
+//!
+//! Lists the foreground/background pairs the renderer relies on (see
+//! [`Theme::contrast_checks`]) in a read-only buffer, those below the
+//! contrast they need first, each with the nearest color of the same hue
+//! that would pass.
+
+use ratatui::style::Color;
+use rust_i18n::t;
+use serde_json::json;
+
+use super::Editor;
+use crate::primitives::text_property::TextPropertyEntry;
+use crate::view::theme::{ContrastCheck, HIGH_CONTRAST_SUFFIX};
+
+/// Name of the report buffer, reused while it is open
+const THEME_CONTRAST_BUFFER_NAME: &str = "*Theme Contrast*";
+
+/// A color as `#rrggbb`, or its name for palette colors
+fn color_label(color: Color) -> String {
+    match color {
+        Color::Rgb(r, g, b) => format!("#{:02x}{:02x}{:02x}", r, g, b),
+        other => format!("{:?}", other),
+    }
+}
+
+/// One line of the report
+fn check_line(check: &ContrastCheck) -> String {
+    let mut line = format!(
+        "  {:<24} {:<26} {:>5.2}:1  ({}:1)",
+        check.foreground, check.background, check.ratio, check.required
+    );
+    if let Some(suggestion) = check.suggestion {
+        line.push_str("  ");
+        line.push_str(&t!(
+            "theme_contrast.suggestion",
+            color = color_label(suggestion)
+        ));
+    }
+    line.push('\n');
+    line
+}
+
+impl Editor {
+    /// Show the contrast of the active theme's color pairs in a read-only
+    /// buffer
+    pub fn show_theme_contrast(&mut self) {
+        let existing = self
+            .buffer_metadata
+            .iter()
+            .find(|(_, m)| m.display_name == THEME_CONTRAST_BUFFER_NAME)
+            .map(|(id, _)| *id);
+        let buffer_id = existing.unwrap_or_else(|| {
+            let buffer_id = self.create_virtual_buffer(
+                THEME_CONTRAST_BUFFER_NAME.to_string(),
+                "special".to_string(),
+                true,
+            );
+            if let Some(state) = self.buffers.get_mut(&buffer_id) {
+                state.editing_disabled = true;
+                state.margins.set_line_numbers(false);
+            }
+            buffer_id
+        });
+
+        let entries = self.theme_contrast_report();
+        if let Err(e) = self.set_virtual_buffer_content(buffer_id, entries) {
+            tracing::error!("Failed to render theme contrast: {}", e);
+        }
+        self.set_active_buffer(buffer_id);
+    }
+
+    /// Lines of the theme contrast report
+    fn theme_contrast_report(&self) -> Vec<TextPropertyEntry> {
+        let header = |text: String| {
+            TextPropertyEntry::text(format!("{}\n", text)).with_property("type", json!("header"))
+        };
+        let checks = self.theme.contrast_checks();
+        let (failing, passing): (Vec<_>, Vec<_>) = checks.iter().partition(|c| !c.passes());
+
+        let mut entries = vec![header(
+            t!("theme_contrast.title", theme = self.theme.name).to_string(),
+        )];
+        let summary = if failing.is_empty() {
+            t!("theme_contrast.all_pass", total = checks.len())
+        } else {
+            t!(
+                "theme_contrast.summary",
+                failing = failing.len(),
+                total = checks.len()
+            )
+        };
+        entries.push(TextPropertyEntry::text(format!("{}\n", summary)));
+        if !failing.is_empty() && !self.theme.name.ends_with(HIGH_CONTRAST_SUFFIX) {
+            entries.push(TextPropertyEntry::text(format!(
+                "{}\n",
+                t!(
+                    "theme_contrast.hint",
+                    variant = format!("{}{}", self.theme.name, HIGH_CONTRAST_SUFFIX)
+                )
+            )));
+        }
+
+        for (title, checks) in [
+            (t!("theme_contrast.failing"), &failing),
+            (t!("theme_contrast.passing"), &passing),
+        ] {
+            if checks.is_empty() {
+                continue;
+            }
+            entries.push(TextPropertyEntry::text("\n"));
+            entries.push(header(title.to_string()));
+            for check in checks {
+                entries.push(
+                    TextPropertyEntry::text(check_line(check))
+                        .with_property("foreground", json!(check.foreground))
+                        .with_property("background", json!(check.background)),
+                );
+            }
+        }
+        entries
+    }
+}
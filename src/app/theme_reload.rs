@@ -0,0 +1,167 @@
+//! Live reloading of the active theme's file
+//!
+//! The JSON file the active theme was loaded from is watched like a plugin
+//! path watch. When it settles after a change it is parsed again and, if that
+//! succeeds, replaces the current theme. A file that fails to parse is tried
+//! once more after [`THEME_RETRY_DELAY`], since some editors save in several
+//! writes; if it still fails the current theme is kept and the parse error is
+//! shown.
+//!
+//! Hardcoded themes have no file and are not watched.
+
+use super::Editor;
+use crate::app::path_watches::PATH_WATCH_POLL_INTERVAL;
+use crate::services::path_watch::{PathChangeKind, PathWatch};
+use crate::services::plugins::hooks::HookArgs;
+use crate::view::notifications::NotificationSeverity;
+use crate::view::theme::Theme;
+use rust_i18n::t;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// Delay before a theme file that failed to parse is read again
+pub(crate) const THEME_RETRY_DELAY: Duration = Duration::from_millis(300);
+
+/// Watch of the file the active theme was loaded from
+pub(crate) struct ThemeWatch {
+    path: PathBuf,
+    watch: PathWatch,
+    /// When to parse the file again after a failed attempt
+    retry_at: Option<Instant>,
+}
+
+impl Editor {
+    /// Follow a theme switch: watch the new theme's file and tell plugins
+    pub(super) fn theme_changed(&mut self) {
+        self.sync_theme_watch();
+        self.full_redraw_requested = true;
+        self.plugin_manager.run_hook(
+            "theme_changed",
+            HookArgs::ThemeChanged {
+                name: self.theme.name.clone(),
+            },
+        );
+    }
+
+    /// Point the theme watch at the file of the configured theme
+    pub(super) fn sync_theme_watch(&mut self) {
+        let path = self.theme_source_path();
+        if self.theme_watch.as_ref().map(|w| &w.path) == path.as_ref() {
+            return;
+        }
+        self.theme_watch = path.and_then(|path| {
+            match PathWatch::new(&path.to_string_lossy(), &self.working_dir) {
+                Ok(watch) => Some(ThemeWatch {
+                    path,
+                    watch,
+                    retry_at: None,
+                }),
+                Err(e) => {
+                    tracing::warn!("Cannot watch theme file {}: {}", path.display(), e);
+                    None
+                }
+            }
+        });
+    }
+
+    /// File the configured theme is loaded from, None for hardcoded themes
+    fn theme_source_path(&self) -> Option<PathBuf> {
+        Theme::source_path_in(
+            &self.configured_theme_name(),
+            Some(&self.dir_context.themes_dir()),
+        )
+    }
+
+    /// Reload the theme file if it changed on disk
+    ///
+    /// Returns true if the screen needs redrawing.
+    pub(super) fn poll_theme_file(&mut self) -> bool {
+        let now = self.time_source.now();
+        let Some(theme_watch) = self.theme_watch.as_mut() else {
+            return false;
+        };
+
+        if let Some(retry_at) = theme_watch.retry_at {
+            if now < retry_at {
+                return false;
+            }
+            theme_watch.retry_at = None;
+            let path = theme_watch.path.clone();
+            return self.reload_theme_file(&path, false);
+        }
+
+        if self.time_source.elapsed_since(self.last_theme_poll) < PATH_WATCH_POLL_INTERVAL {
+            return false;
+        }
+        self.last_theme_poll = now;
+
+        let changed = theme_watch
+            .watch
+            .poll()
+            .iter()
+            .any(|change| change.kind != PathChangeKind::Removed);
+        if !changed {
+            return false;
+        }
+        let path = theme_watch.path.clone();
+        self.reload_theme_file(&path, true)
+    }
+
+    /// Reload the active theme from its file on request
+    pub(super) fn reload_theme(&mut self) {
+        match self.theme_source_path() {
+            Some(path) => {
+                self.reload_theme_file(&path, false);
+            }
+            None => self.set_status_message(
+                t!("view.theme_no_file", theme = self.configured_theme_name()).to_string(),
+            ),
+        }
+    }
+
+    /// Parse `path` and make it the active theme
+    ///
+    /// On a parse error either schedules one more attempt (`retry`) or keeps
+    /// the current theme and reports the error. Returns true if the screen
+    /// needs redrawing.
+    fn reload_theme_file(&mut self, path: &Path, retry: bool) -> bool {
+        match Theme::from_file(path) {
+            Ok(mut theme) => {
+                if self.config.ui.transparent_background {
+                    theme.make_background_transparent();
+                }
+                self.theme = theme;
+                self.apply_glyphs();
+                self.theme_changed();
+                self.set_status_message(
+                    t!("view.theme_reloaded", theme = self.theme.name.clone()).to_string(),
+                );
+                true
+            }
+            Err(e) if retry => {
+                tracing::debug!(
+                    "Theme file {} did not parse, retrying: {}",
+                    path.display(),
+                    e
+                );
+                if let Some(theme_watch) = self.theme_watch.as_mut() {
+                    theme_watch.retry_at = Some(self.time_source.now() + THEME_RETRY_DELAY);
+                }
+                false
+            }
+            Err(e) => {
+                tracing::warn!("Keeping current theme, {} failed: {}", path.display(), e);
+                self.notify(
+                    NotificationSeverity::Error,
+                    t!(
+                        "view.theme_reload_failed",
+                        path = path.display().to_string(),
+                        error = e
+                    )
+                    .to_string(),
+                );
+                true
+            }
+        }
+    }
+}
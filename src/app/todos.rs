@@ -0,0 +1,365 @@
+//! TODO/FIXME comment index and highlighting
+//!
+//! "List TODOs in Project" scans the working directory on the worker pool
+//! (see [`crate::services::todo_scanner`]) and shows the matches grouped by
+//! file in a read-only virtual buffer; Enter jumps to the comment under the
+//! cursor. Open buffers additionally get their keywords highlighted inside
+//! comments, one line at a time as lines appear on screen or change.
+
+use crate::model::event::BufferId;
+use crate::primitives::text_property::TextPropertyEntry;
+use crate::services::plugins::hooks::LineInfo;
+use crate::services::todo_scanner::{
+    self, comment_ranges, in_comment, knows_comments, TodoItem, TodoMatcher, TodoScanRequest,
+};
+use crate::services::worker_pool::JobHandle;
+use crate::view::overlay::{Overlay, OverlayFace, OverlayNamespace};
+use ratatui::style::Color;
+use rust_i18n::t;
+use serde_json::json;
+use std::collections::HashMap;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use super::Editor;
+
+/// Display name of the TODO list buffer
+pub(crate) const TODOS_BUFFER_NAME: &str = "*TODOs*";
+
+/// Buffer mode of the TODO list buffer (bindings live in `ModeRegistry::new`)
+pub(crate) const TODOS_MODE: &str = "todos";
+
+/// Namespace of the keyword highlights in open buffers
+fn todo_namespace() -> OverlayNamespace {
+    OverlayNamespace::from_string("todo-keywords".to_string())
+}
+
+/// An open TODO list
+pub(crate) struct TodoPanel {
+    buffer_id: BufferId,
+    /// Result of the last finished scan (None until the first one finishes)
+    items: Option<Vec<TodoItem>>,
+    /// Scan in progress and its id
+    scan: Option<(u64, JobHandle)>,
+}
+
+impl TodoPanel {
+    /// Build the list buffer content
+    fn entries(&self, root: &Path, keywords: &[String]) -> Vec<TextPropertyEntry> {
+        let mut entries = vec![TextPropertyEntry::text(format!(
+            "{}\n",
+            t!("todos.title", path = root.display().to_string())
+        ))
+        .with_property("type", json!("header"))];
+
+        let items = self.items.as_deref().unwrap_or_default();
+        let mut counts = keywords
+            .iter()
+            .map(|word| {
+                let count = items.iter().filter(|item| &item.keyword == word).count();
+                format!("{}: {}", word, count)
+            })
+            .collect::<Vec<_>>()
+            .join("  ");
+        if self.scan.is_some() {
+            counts.push_str(&format!("  ({})", t!("todos.scanning")));
+        }
+        entries.push(
+            TextPropertyEntry::text(format!("{}\n", counts)).with_property("type", json!("header")),
+        );
+        entries.push(
+            TextPropertyEntry::text(format!("{}\n\n", t!("todos.help")))
+                .with_property("type", json!("help")),
+        );
+
+        if self.items.is_some() && items.is_empty() {
+            entries.push(TextPropertyEntry::text(format!(
+                "  {}\n",
+                t!("todos.empty")
+            )));
+        }
+
+        let mut start = 0;
+        while start < items.len() {
+            let path = &items[start].path;
+            let end = start
+                + items[start..]
+                    .iter()
+                    .take_while(|item| &item.path == path)
+                    .count();
+            let display = path.strip_prefix(root).unwrap_or(path).display();
+            entries.push(
+                TextPropertyEntry::text(format!("{} ({})\n", display, end - start))
+                    .with_property("type", json!("file"))
+                    .with_property("file", json!(path.to_string_lossy())),
+            );
+            for item in &items[start..end] {
+                let author = item
+                    .author
+                    .as_ref()
+                    .map(|author| format!("({}) ", author))
+                    .unwrap_or_default();
+                entries.push(
+                    TextPropertyEntry::text(format!(
+                        "  {:>8}  {:<6} {}{}\n",
+                        format!("{}:{}", item.line, item.column),
+                        item.keyword,
+                        author,
+                        item.text
+                    ))
+                    .with_property("type", json!("item"))
+                    .with_property("file", json!(path.to_string_lossy()))
+                    .with_property("line", json!(item.line))
+                    .with_property("column", json!(item.column)),
+                );
+            }
+            start = end;
+        }
+
+        entries
+    }
+}
+
+impl Editor {
+    /// Open the TODO list and scan the project for keyword comments
+    pub fn list_todos(&mut self) {
+        if let Some(panel) = &self.todo_panel {
+            let buffer_id = panel.buffer_id;
+            self.set_active_buffer(buffer_id);
+            self.start_todo_scan();
+            return;
+        }
+
+        let buffer_id =
+            self.create_virtual_buffer(TODOS_BUFFER_NAME.to_string(), TODOS_MODE.to_string(), true);
+        if let Some(state) = self.buffers.get_mut(&buffer_id) {
+            state.editing_disabled = true;
+            state.margins.set_line_numbers(false);
+        }
+        self.todo_panel = Some(TodoPanel {
+            buffer_id,
+            items: None,
+            scan: None,
+        });
+        self.set_active_buffer(buffer_id);
+        self.start_todo_scan();
+    }
+
+    /// Scan again, opening the TODO list if it isn't open
+    pub fn refresh_todos(&mut self) {
+        if self.todo_panel.is_some() {
+            self.start_todo_scan();
+        } else {
+            self.list_todos();
+        }
+    }
+
+    /// Start a scan, replacing any scan in progress
+    fn start_todo_scan(&mut self) {
+        static NEXT_SCAN_ID: AtomicU64 = AtomicU64::new(1);
+
+        let open_buffers: HashMap<PathBuf, String> = self
+            .buffer_metadata
+            .iter()
+            .filter_map(|(id, metadata)| {
+                let path = metadata.file_path()?;
+                let text = self.buffers.get(id)?.buffer.to_string()?;
+                Some((path.clone(), text))
+            })
+            .collect();
+        let request = TodoScanRequest {
+            scan_id: NEXT_SCAN_ID.fetch_add(1, Ordering::Relaxed),
+            root: self.working_dir.clone(),
+            matcher: TodoMatcher::new(&self.config.todos.keywords),
+            open_buffers,
+            grammar_registry: self.grammar_registry.clone(),
+            languages: self.config.languages.clone(),
+            theme: self.theme.clone(),
+        };
+        let scan_id = request.scan_id;
+        let job = todo_scanner::start_scan(&self.worker_pool, request);
+
+        let Some(panel) = self.todo_panel.as_mut() else {
+            job.cancel();
+            return;
+        };
+        if let Some((_, previous)) = panel.scan.replace((scan_id, job)) {
+            previous.cancel();
+        }
+        self.render_todo_panel();
+        self.set_status_message(t!("todos.scanning").to_string());
+    }
+
+    /// Show the result of a finished scan
+    pub(super) fn handle_todo_scan_finished(&mut self, scan_id: u64, items: Vec<TodoItem>) {
+        let Some(panel) = self.todo_panel.as_mut() else {
+            return;
+        };
+        if panel.scan.as_ref().map(|(id, _)| *id) != Some(scan_id) {
+            return;
+        }
+        panel.scan = None;
+        let count = items.len();
+        let files = items
+            .iter()
+            .map(|item| &item.path)
+            .collect::<std::collections::HashSet<_>>()
+            .len();
+        panel.items = Some(items);
+        let buffer_id = panel.buffer_id;
+        self.render_todo_panel();
+        self.set_status_message(t!("todos.found", count = count, files = files).to_string());
+
+        // Start on the first item rather than the header
+        if let Some(state) = self.buffers.get_mut(&buffer_id) {
+            let first_item = state
+                .text_properties
+                .all()
+                .iter()
+                .find(|p| p.get("type") == Some(&json!("item")))
+                .map(|p| p.start);
+            if let Some(position) = first_item {
+                if state.cursors.primary().position < position {
+                    state.cursors.primary_mut().position = position;
+                }
+            }
+        }
+    }
+
+    /// Re-render the list buffer from the panel state
+    fn render_todo_panel(&mut self) {
+        let Some(panel) = &self.todo_panel else {
+            return;
+        };
+        let buffer_id = panel.buffer_id;
+        let matcher = TodoMatcher::new(&self.config.todos.keywords);
+        let entries = panel.entries(&self.working_dir, matcher.keywords());
+        if let Err(e) = self.set_virtual_buffer_content(buffer_id, entries) {
+            tracing::error!("Failed to render TODO list: {}", e);
+        }
+    }
+
+    /// Jump to the comment (or file) under the cursor in the TODO list
+    pub(super) fn todos_open(&mut self) {
+        let Some(panel) = &self.todo_panel else {
+            return;
+        };
+        if self.active_buffer() != panel.buffer_id {
+            return;
+        }
+        let target = self.get_text_properties_at_cursor().and_then(|properties| {
+            properties.into_iter().find_map(|p| {
+                let path = PathBuf::from(p.get("file")?.as_str()?);
+                let line = p.get("line").and_then(|v| v.as_u64()).unwrap_or(1);
+                let column = p.get("column").and_then(|v| v.as_u64()).unwrap_or(1);
+                Some((path, line as usize, column as usize))
+            })
+        });
+        let Some((path, line, column)) = target else {
+            return;
+        };
+        match self.open_file(&path) {
+            Ok(_) => self.goto_line_col(line, Some(column)),
+            Err(e) => {
+                self.set_status_message(t!("file.error_opening", error = e.to_string()).to_string())
+            }
+        }
+    }
+
+    /// Forget the TODO list when its buffer is closed
+    pub(super) fn on_todos_buffer_closed(&mut self, buffer_id: BufferId) {
+        if self
+            .todo_panel
+            .as_ref()
+            .is_some_and(|panel| panel.buffer_id == buffer_id)
+        {
+            if let Some((_, job)) = self.todo_panel.take().and_then(|panel| panel.scan) {
+                job.cancel();
+            }
+        }
+    }
+
+    /// Highlight keywords inside comments on lines that appeared or changed
+    ///
+    /// `visible` is the byte range on screen; comments are located by
+    /// highlighting the same range as the renderer so its cache is reused.
+    pub(super) fn highlight_todo_keywords(
+        &mut self,
+        buffer_id: BufferId,
+        lines: &[LineInfo],
+        visible: Range<usize>,
+    ) {
+        if self
+            .buffer_metadata
+            .get(&buffer_id)
+            .is_none_or(|metadata| metadata.is_virtual())
+        {
+            return;
+        }
+        let matcher = TodoMatcher::new(&self.config.todos.keywords);
+        let colors: Vec<Color> = self
+            .config
+            .todos
+            .keywords
+            .iter()
+            .map(|k| Color::Rgb(k.color[0], k.color[1], k.color[2]))
+            .collect();
+        let context_bytes = self.config.editor.highlight_context_bytes;
+        let Some(state) = self.buffers.get_mut(&buffer_id) else {
+            return;
+        };
+
+        let comments = knows_comments(&state.highlighter).then(|| {
+            let size = visible.len();
+            let spans = state.highlighter.highlight_viewport(
+                &state.buffer,
+                visible.start.saturating_sub(size),
+                visible.end.saturating_add(size).min(state.buffer.len()),
+                &self.theme,
+                context_bytes,
+            );
+            comment_ranges(&spans)
+        });
+
+        let namespace = todo_namespace();
+        for line in lines {
+            state.overlays.clear_namespace_in_range(
+                &namespace,
+                &(line.byte_start..line.byte_end),
+                &mut state.marker_list,
+            );
+            for found in matcher.match_line(&line.content) {
+                let range = line.byte_start + found.range.start..line.byte_start + found.range.end;
+                if comments
+                    .as_deref()
+                    .is_some_and(|comments| !in_comment(comments, &range))
+                {
+                    continue;
+                }
+                let overlay = Overlay::with_namespace(
+                    &mut state.marker_list,
+                    range,
+                    OverlayFace::Foreground {
+                        color: colors[found.keyword],
+                    },
+                    namespace.clone(),
+                );
+                state.overlays.add(overlay, &state.marker_list);
+            }
+        }
+    }
+
+    /// Drop all keyword highlights so they are redone with the current config
+    pub(super) fn reset_todo_highlights(&mut self) {
+        let namespace = todo_namespace();
+        for state in self.buffers.values_mut() {
+            state
+                .overlays
+                .clear_namespace(&namespace, &mut state.marker_list);
+        }
+        // Lines are only highlighted when they are new to the screen
+        self.seen_byte_ranges.clear();
+        self.render_todo_panel();
+    }
+}
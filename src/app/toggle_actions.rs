@@ -3,14 +3,16 @@
 //! This module contains toggle methods and configuration operations:
 //! - Toggle line numbers, debug highlights, menu bar
 //! - Toggle mouse capture, mouse hover, inlay hints
+//! - Cycle whitespace rendering
 //! - Reset buffer settings
 //! - Config dump, save, and reload
 
 use rust_i18n::t;
 
-use crate::config::Config;
-use crate::input::keybindings::KeybindingResolver;
+use crate::config::{Config, WhitespaceRendering};
+use crate::config_io::ConfigLayer;
 use crate::services::lsp::manager::detect_language;
+use crate::view::terminal_effects::TerminalEffect;
 
 use super::Editor;
 
@@ -72,29 +74,31 @@ impl Editor {
             .and_then(|m| m.file_path().cloned());
 
         // Determine settings from config (with language fallback)
-        let (tab_size, use_tabs, show_whitespace_tabs) = if let Some(path) = &file_path {
-            if let Some(language) = detect_language(path, &self.config.languages) {
-                if let Some(lang_config) = self.config.languages.get(&language) {
-                    (
-                        lang_config.tab_size.unwrap_or(self.config.editor.tab_size),
-                        lang_config.use_tabs,
-                        lang_config.show_whitespace_tabs,
-                    )
-                } else {
-                    (self.config.editor.tab_size, false, true)
-                }
-            } else {
-                (self.config.editor.tab_size, false, true)
-            }
+        let language = file_path
+            .as_ref()
+            .and_then(|path| detect_language(path, &self.config.languages));
+        let (tab_size, use_tabs, show_whitespace_tabs) = if let Some(lang_config) = language
+            .as_ref()
+            .and_then(|language| self.config.languages.get(language))
+        {
+            (
+                lang_config.tab_size.unwrap_or(self.config.editor.tab_size),
+                lang_config.use_tabs,
+                lang_config.show_whitespace_tabs,
+            )
         } else {
             (self.config.editor.tab_size, false, true)
         };
+        let indent_rules = language
+            .as_ref()
+            .and_then(|language| self.indent_rules_for_language(language));
 
         // Apply settings to buffer
         if let Some(state) = self.buffers.get_mut(&buffer_id) {
             state.tab_size = tab_size;
             state.use_tabs = use_tabs;
             state.show_whitespace_tabs = show_whitespace_tabs;
+            state.indent_rules = indent_rules;
         }
 
         self.set_status_message(t!("toggle.buffer_settings_reset").to_string());
@@ -102,15 +106,13 @@ impl Editor {
 
     /// Toggle mouse capture on/off
     pub fn toggle_mouse_capture(&mut self) {
-        use std::io::stdout;
-
         self.mouse_enabled = !self.mouse_enabled;
+        self.terminal_effects
+            .push(TerminalEffect::MouseCapture(self.mouse_enabled));
 
         if self.mouse_enabled {
-            let _ = crossterm::execute!(stdout(), crossterm::event::EnableMouseCapture);
             self.set_status_message(t!("toggle.mouse_capture_enabled").to_string());
         } else {
-            let _ = crossterm::execute!(stdout(), crossterm::event::DisableMouseCapture);
             self.set_status_message(t!("toggle.mouse_capture_disabled").to_string());
         }
     }
@@ -165,20 +167,24 @@ impl Editor {
         }
     }
 
+    /// Cycle whitespace rendering: none, trailing, boundary, all
+    pub fn toggle_render_whitespace(&mut self) {
+        let (next, name) = match self.config.ui.render_whitespace {
+            WhitespaceRendering::None => (WhitespaceRendering::Trailing, "trailing"),
+            WhitespaceRendering::Trailing => (WhitespaceRendering::Boundary, "boundary"),
+            WhitespaceRendering::Boundary => (WhitespaceRendering::All, "all"),
+            WhitespaceRendering::All => (WhitespaceRendering::None, "none"),
+        };
+        self.config.ui.render_whitespace = next;
+        self.set_status_message(t!("toggle.render_whitespace", mode = name).to_string());
+    }
+
     /// Dump the current configuration to the user's config file
     pub fn dump_config(&mut self) {
-        // Create the config directory if it doesn't exist
-        if let Err(e) = std::fs::create_dir_all(&self.dir_context.config_dir) {
-            self.set_status_message(
-                t!("error.config_dir_failed", error = e.to_string()).to_string(),
-            );
-            return;
-        }
-
         let config_path = self.dir_context.config_path();
 
         // Save the config
-        match self.config.save_to_file(&config_path) {
+        match self.persist_config(ConfigLayer::User) {
             Ok(()) => {
                 // Open the saved config file in a new buffer
                 match self.open_file(&config_path) {
@@ -203,17 +209,11 @@ impl Editor {
         }
     }
 
-    /// Save the current configuration to file (without opening it)
+    /// Save the current configuration to the user's config file (without opening it)
     ///
     /// Returns Ok(()) on success, or an error message on failure
-    pub fn save_config(&self) -> Result<(), String> {
-        // Create the config directory if it doesn't exist
-        std::fs::create_dir_all(&self.dir_context.config_dir)
-            .map_err(|e| format!("Failed to create config directory: {}", e))?;
-
-        let config_path = self.dir_context.config_path();
-        self.config
-            .save_to_file(&config_path)
+    pub fn save_config(&mut self) -> Result<(), String> {
+        self.persist_config(ConfigLayer::User)
             .map_err(|e| format!("Failed to save config: {}", e))
     }
 
@@ -221,29 +221,14 @@ impl Editor {
     ///
     /// This reloads the config from disk, applies runtime changes (theme, keybindings),
     /// and emits a config_changed event so plugins can update their state accordingly.
-    /// Checks local config (working directory) first, then system config paths.
+    /// Checks local config (working directory) first, then the user config.
     pub fn reload_config(&mut self) {
-        let old_theme = self.config.theme.clone();
-        self.config = Config::load_for_working_dir(&self.working_dir);
-
-        // Apply theme change if needed
-        if old_theme != self.config.theme {
-            self.theme = crate::view::theme::Theme::from_name(&self.config.theme);
-            tracing::info!("Theme changed to '{}'", self.config.theme.0);
-        }
-
-        // Always reload keybindings (complex types don't implement PartialEq)
-        self.keybindings = KeybindingResolver::new(&self.config);
-
-        // Update LSP configs
-        if let Some(ref mut lsp) = self.lsp {
-            for (language, lsp_config) in &self.config.lsp {
-                lsp.set_language_config(language.clone(), lsp_config.clone());
-            }
-        }
+        let config = Config::load_for_working_dir(&self.dir_context, &self.working_dir);
+        self.replace_config(config);
+        self.record_config_on_disk();
 
         // Emit event so plugins know config changed
-        let config_path = Config::find_config_path(&self.working_dir);
+        let config_path = Config::find_config_path(&self.dir_context, &self.working_dir);
         self.emit_event(
             "config_changed",
             serde_json::json!({
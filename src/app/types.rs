@@ -2,10 +2,10 @@ use crate::app::file_open::SortMode;
 use crate::input::keybindings::Action;
 use crate::model::event::{BufferId, SplitDirection, SplitId};
 use crate::services::async_bridge::LspMessageType;
+use crate::services::file_access::ReadOnlyReason;
 use ratatui::layout::Rect;
 use rust_i18n::t;
 use std::collections::{HashMap, HashSet};
-use std::ops::Range;
 use std::path::{Path, PathBuf};
 
 pub const DEFAULT_BACKGROUND_FILE: &str = "scripts/landscape-wide.txt";
@@ -22,21 +22,6 @@ pub(super) struct EventLineInfo {
     pub line_delta: i32,
 }
 
-/// Search state for find/replace functionality
-#[derive(Debug, Clone)]
-pub(super) struct SearchState {
-    /// The search query
-    pub query: String,
-    /// All match positions in the buffer (byte offsets)
-    pub matches: Vec<usize>,
-    /// Index of the currently selected match
-    pub current_match_index: Option<usize>,
-    /// Whether search wraps around at document boundaries
-    pub wrap_search: bool,
-    /// Optional search range (for search in selection)
-    pub search_range: Option<Range<usize>>,
-}
-
 /// A bookmark in the editor (position in a specific buffer)
 #[derive(Debug, Clone)]
 pub(super) struct Bookmark {
@@ -53,8 +38,12 @@ pub(super) struct InteractiveReplaceState {
     pub search: String,
     /// The replacement text
     pub replacement: String,
+    /// How the search text is matched and replaced
+    pub matcher: super::search_case::ReplaceMatcher,
     /// Current match position (byte offset of the match we're at)
     pub current_match_pos: usize,
+    /// Length in bytes of the current match
+    pub current_match_len: usize,
     /// Starting position (to detect when we've wrapped around full circle)
     pub start_pos: usize,
     /// Whether we've wrapped around to the beginning
@@ -103,14 +92,61 @@ pub struct BufferMetadata {
     /// Binary buffers are automatically read-only and render unprintable chars as code points
     pub binary: bool,
 
+    /// Why the file on disk can't be written, if it can't. The buffer is
+    /// read-only while this is set, unless "Edit Anyway" unlocked it.
+    pub fs_read_only: Option<ReadOnlyReason>,
+
+    /// "Edit Anyway" unlocked the buffer even though its file can't be written
+    pub edit_anyway: bool,
+
+    /// The `files.protected_globs` rule the file matches, if any; see
+    /// [`ProtectedFile`]
+    pub protected: Option<ProtectedFile>,
+
     /// LSP server instance IDs that have received didOpen for this buffer.
     /// Used to ensure didOpen is sent before any requests to a new/restarted server.
     /// When a server restarts, it gets a new ID, so didOpen is automatically resent.
     /// Old IDs are harmless - they just remain in the set but don't match any active server.
     pub lsp_opened_with: HashSet<u64>,
+
+    /// Per-buffer auto-revert setting; `None` follows `files.auto_revert`
+    pub auto_revert: Option<bool>,
+
+    /// Keep the cursor and view at the end of the file as it grows on disk,
+    /// like `tail -f`
+    pub follow_tail: bool,
+
+    /// Pinned buffers are skipped by bulk close commands and never closed
+    /// automatically
+    pub pinned: bool,
+}
+
+/// Protection of a file matching `files.protected_globs`
+///
+/// The buffer is read-only until "Unlock Protected File" unlocks it, and the
+/// first save after that asks for confirmation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProtectedFile {
+    /// The glob the file matched
+    pub rule: String,
+    /// "Unlock Protected File" was confirmed
+    pub unlocked: bool,
+    /// Saving since the unlock was confirmed
+    pub save_confirmed: bool,
 }
 
 impl BufferMetadata {
+    /// Whether edits are refused because the file can't be written or is
+    /// protected, and wasn't unlocked
+    pub fn file_locked(&self) -> bool {
+        (self.fs_read_only.is_some() && !self.edit_anyway) || self.protected_locked()
+    }
+
+    /// Whether the file is protected and not unlocked
+    pub fn protected_locked(&self) -> bool {
+        self.protected.as_ref().is_some_and(|p| !p.unlocked)
+    }
+
     /// Get the file path if this is a file-backed buffer
     pub fn file_path(&self) -> Option<&PathBuf> {
         match &self.kind {
@@ -154,7 +190,13 @@ impl BufferMetadata {
             lsp_disabled_reason: None,
             read_only: false,
             binary: false,
+            fs_read_only: None,
+            edit_anyway: false,
+            protected: None,
             lsp_opened_with: HashSet::new(),
+            auto_revert: None,
+            follow_tail: false,
+            pinned: false,
         }
     }
 
@@ -171,7 +213,13 @@ impl BufferMetadata {
             lsp_disabled_reason: Some(t!("lsp.disabled.unnamed").to_string()),
             read_only: false,
             binary: false,
+            fs_read_only: None,
+            edit_anyway: false,
+            protected: None,
             lsp_opened_with: HashSet::new(),
+            auto_revert: None,
+            follow_tail: false,
+            pinned: false,
         }
     }
 
@@ -182,9 +230,7 @@ impl BufferMetadata {
     /// * `working_dir` - The canonical working directory for computing relative display name
     pub fn with_file(path: PathBuf, working_dir: &Path) -> Self {
         // Compute URI from the absolute path
-        let file_uri = url::Url::from_file_path(&path)
-            .ok()
-            .and_then(|u| u.as_str().parse::<lsp_types::Uri>().ok());
+        let file_uri = crate::services::lsp::uri::path_to_uri(&path);
 
         // Compute display name (project-relative when under working_dir, else absolute path).
         // Use canonicalized forms first to handle macOS /var -> /private/var differences.
@@ -200,12 +246,23 @@ impl BufferMetadata {
             lsp_disabled_reason: None,
             read_only: false,
             binary: false,
+            fs_read_only: None,
+            edit_anyway: false,
+            protected: None,
             lsp_opened_with: HashSet::new(),
+            auto_revert: None,
+            follow_tail: false,
+            pinned: false,
         }
     }
 
     /// Compute display name relative to working_dir when possible, otherwise absolute
     fn display_name_for_path(path: &Path, working_dir: &Path) -> String {
+        // Remote files are shown by their URL
+        if crate::services::file_provider::is_remote_path(path) {
+            return path.display().to_string();
+        }
+
         // Canonicalize working_dir to normalize platform-specific prefixes
         let canonical_working_dir = working_dir
             .canonicalize()
@@ -248,7 +305,13 @@ impl BufferMetadata {
             lsp_disabled_reason: Some(t!("lsp.disabled.virtual").to_string()),
             read_only,
             binary: false,
+            fs_read_only: None,
+            edit_anyway: false,
+            protected: None,
             lsp_opened_with: HashSet::new(),
+            auto_revert: None,
+            follow_tail: false,
+            pinned: false,
         }
     }
 
@@ -537,6 +600,8 @@ pub(super) struct MouseState {
     pub drag_selection_anchor: Option<usize>,
     /// Tab drag state (for drag-to-split functionality)
     pub dragging_tab: Option<TabDragState>,
+    /// Column of cursors being placed by a modified drag
+    pub column_drag: Option<super::mouse_cursors::ColumnDrag>,
 }
 
 /// Mapping from visual row to buffer positions for mouse click handling
@@ -590,6 +655,9 @@ pub(crate) struct CachedLayout {
     /// Maximize split button hit areas
     /// (split_id, row, start_col, end_col)
     pub maximize_split_areas: Vec<(SplitId, u16, u16, u16)>,
+    /// Winbar areas; clicking one focuses its split
+    /// (split_id, buffer_id, winbar_rect)
+    pub winbar_areas: Vec<(SplitId, BufferId, Rect)>,
     /// View line mappings for accurate mouse click positioning per split
     /// Maps visual row index to character position mappings
     /// Used to translate screen coordinates to buffer byte positions
@@ -602,8 +670,14 @@ pub(crate) struct CachedLayout {
     pub status_bar_lsp_area: Option<(u16, u16, u16)>,
     /// Status bar warning badge area (row, start_col, end_col)
     pub status_bar_warning_area: Option<(u16, u16, u16)>,
+    /// Status bar blocked project commands badge area (row, start_col, end_col)
+    pub status_bar_blocked_area: Option<(u16, u16, u16)>,
+    /// Status bar progress indicator area (row, start_col, end_col)
+    pub status_bar_progress_area: Option<(u16, u16, u16)>,
     /// Status bar line ending indicator area (row, start_col, end_col)
     pub status_bar_line_ending_area: Option<(u16, u16, u16)>,
     /// Search options layout for checkbox hit testing
     pub search_options_layout: Option<crate::view::ui::status_bar::SearchOptionsLayout>,
+    /// Notification and notification button areas
+    pub notification_layout: crate::view::notifications::NotificationLayout,
 }
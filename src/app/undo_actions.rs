@@ -1,20 +1,31 @@
 //! Undo and redo action handlers.
 
 use super::Editor;
+use crate::model::event::{Event, UndoPolicy};
+use crate::state::EditorState;
 use rust_i18n::t;
 
 impl Editor {
+    /// How undo and redo group plugin edits, from `editor.undo_skips_plugin_edits`
+    fn undo_policy(&self) -> UndoPolicy {
+        UndoPolicy::from_config(self.config.editor.undo_skips_plugin_edits)
+    }
+
     /// Handle Undo action - revert the last edit operation.
+    ///
+    /// A labeled step, such as a replace-all, is named in the status bar.
     pub fn handle_undo(&mut self) {
         if self.is_editing_disabled() {
             self.set_status_message(t!("buffer.editing_disabled").to_string());
             return;
         }
 
+        let policy = self.undo_policy();
         let event_log = self.active_event_log_mut();
         let before_idx = event_log.current_index();
         let can_undo = event_log.can_undo();
-        let events = event_log.undo();
+        let label = event_log.undo_label_with(policy).map(str::to_string);
+        let events = event_log.undo_with(policy);
         let after_idx = self.active_event_log().current_index();
 
         tracing::debug!(
@@ -33,23 +44,98 @@ impl Editor {
 
         // Update modified status based on event log position
         self.update_modified_from_event_log();
+
+        if let Some(label) = label.filter(|_| !events.is_empty()) {
+            self.set_status_message(t!("undo.undid", label = label).to_string());
+        }
     }
 
     /// Handle Redo action - reapply an undone edit operation.
+    ///
+    /// A labeled step is named in the status bar.
     pub fn handle_redo(&mut self) {
         if self.is_editing_disabled() {
             self.set_status_message(t!("buffer.editing_disabled").to_string());
             return;
         }
 
-        let events = self.active_event_log_mut().redo();
+        let policy = self.undo_policy();
+        let before_idx = self.active_event_log().current_index();
+        let label = self
+            .active_event_log()
+            .redo_label_with(policy)
+            .map(str::to_string);
+        let events = self.active_event_log_mut().redo_with(policy);
+
+        // The buffer may have changed underneath the undone edits (e.g. an edit
+        // that bypassed the log); replaying them would write at stale offsets
+        if !replays_cleanly(self.active_state_mut(), &events) {
+            tracing::warn!(
+                "Redo entries from index {} no longer match the buffer, discarding",
+                before_idx
+            );
+            self.active_event_log_mut().discard_from(before_idx);
+            self.set_status_message(t!("buffer.redo_discarded").to_string());
+            return;
+        }
 
         // Apply all events collected during redo
+        let redone = !events.is_empty();
         for event in events {
             self.apply_event_to_active_buffer(&event);
         }
 
         // Update modified status based on event log position
         self.update_modified_from_event_log();
+
+        if let Some(label) = label.filter(|_| redone) {
+            self.set_status_message(t!("undo.redid", label = label).to_string());
+        }
+    }
+}
+
+/// Whether `events` fit the current buffer: every offset is in bounds, and
+/// the first deletion removes the text it recorded
+fn replays_cleanly(state: &mut EditorState, events: &[Event]) -> bool {
+    let mut len = state.buffer.len();
+    let mut untouched = true;
+    fits(state, events, &mut len, &mut untouched)
+}
+
+fn fits(state: &mut EditorState, events: &[Event], len: &mut usize, untouched: &mut bool) -> bool {
+    for event in events {
+        match event {
+            Event::Insert { position, text, .. } => {
+                if *position > *len {
+                    return false;
+                }
+                *len += text.len();
+                *untouched = false;
+            }
+            Event::Delete {
+                range,
+                deleted_text,
+                ..
+            } => {
+                if range.start > range.end || range.end > *len || range.len() != deleted_text.len()
+                {
+                    return false;
+                }
+                // Later deletions can only be checked against the buffer as
+                // it will be after the earlier events, so bounds must do
+                if *untouched && state.get_text_range(range.start, range.end) != *deleted_text {
+                    return false;
+                }
+                *len -= range.len();
+                *untouched = false;
+            }
+            Event::Batch { events, .. } => {
+                if !fits(state, events, len, untouched) {
+                    return false;
+                }
+            }
+            _ => {}
+        }
     }
+    true
 }
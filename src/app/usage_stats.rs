@@ -0,0 +1,370 @@
+//! Usage statistics
+//!
+//! With `stats.enabled` on, commands run, keys pressed, focused time and
+//! opened files are counted into [`UsageStatsTracker`] and merged into
+//! [`UsageStats`] in the state directory in batches: once input has paused
+//! for [`IDLE_DELAY`] and [`FLUSH_INTERVAL`] has passed since the last write,
+//! and at shutdown. While it is off nothing is counted and the file is never
+//! written. "Usage Statistics" shows the stored and pending counts in a
+//! read-only buffer; "Export Usage Statistics" writes them as JSON.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use chrono::NaiveDate;
+use rust_i18n::t;
+use serde_json::json;
+
+use super::Editor;
+use crate::input::keybindings::Action;
+use crate::primitives::text_property::TextPropertyEntry;
+use crate::services::lsp::manager::detect_language;
+use crate::services::usage_stats::{sparkline, top_counts, UsageStats};
+use crate::view::prompt::PromptType;
+
+/// Time without input before pending counts are written
+const IDLE_DELAY: Duration = Duration::from_secs(2);
+
+/// Least time between two writes of the statistics file
+const FLUSH_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Most days covered by the report's sparkline
+const SPARKLINE_DAYS: u32 = 30;
+
+/// Entries listed per section of the report
+const TOP_ENTRIES: usize = 10;
+
+/// Language counted for files no language config matches
+const UNKNOWN_LANGUAGE: &str = "text";
+
+/// File name offered by "Export Usage Statistics"
+const EXPORT_FILE_NAME: &str = "usage-stats.json";
+
+/// Name of the report buffer, reused while it is open
+const USAGE_STATS_BUFFER_NAME: &str = "*Usage Statistics*";
+
+/// Counts not yet written and the times they depend on
+pub(crate) struct UsageStatsTracker {
+    pending: UsageStats,
+    /// When the terminal gained focus, if it has it; terminals that never
+    /// report focus count as focused
+    focused_since: Option<Instant>,
+    last_input: Instant,
+    last_flush: Instant,
+}
+
+impl UsageStatsTracker {
+    pub(crate) fn new(now: Instant) -> Self {
+        Self {
+            pending: UsageStats::default(),
+            focused_since: Some(now),
+            last_input: now,
+            last_flush: now,
+        }
+    }
+}
+
+/// The local date counts are filed under
+fn today() -> NaiveDate {
+    chrono::Local::now().date_naive()
+}
+
+/// Name a run of `action` is counted under, `None` for actions that aren't
+/// commands (typing is counted as keys)
+fn command_name(action: &Action) -> Option<String> {
+    match action {
+        Action::None | Action::InsertChar(_) => None,
+        Action::PluginAction(name) => Some(name.clone()),
+        _ => {
+            let debug = format!("{:?}", action);
+            let end = debug.find(['(', ' ', '{']).unwrap_or(debug.len());
+            Some(debug[..end].to_string())
+        }
+    }
+}
+
+/// Focused time as hours and minutes, or seconds below a minute
+fn format_duration(secs: u64) -> String {
+    match (secs / 3600, secs % 3600 / 60) {
+        (0, 0) => format!("{}s", secs),
+        (0, minutes) => format!("{}m", minutes),
+        (hours, minutes) => format!("{}h {}m", hours, minutes),
+    }
+}
+
+impl Editor {
+    /// Count a run of `action`
+    pub(super) fn record_usage_action(&mut self, action: &Action) {
+        if !self.config.stats.enabled {
+            return;
+        }
+        if let Some(name) = command_name(action) {
+            self.usage_stats.pending.record_command(today(), &name);
+        }
+    }
+
+    /// Count a key press, postponing the next write
+    pub(super) fn record_usage_key(&mut self) {
+        if !self.config.stats.enabled {
+            return;
+        }
+        self.usage_stats.last_input = self.time_source.now();
+        self.usage_stats.pending.record_key(today());
+    }
+
+    /// Count the file at `path` as opened
+    pub(super) fn record_usage_file_open(&mut self, path: &Path) {
+        if !self.config.stats.enabled {
+            return;
+        }
+        let language = detect_language(path, &self.config.languages)
+            .unwrap_or_else(|| UNKNOWN_LANGUAGE.to_string());
+        self.usage_stats
+            .pending
+            .record_file(today(), &path.to_string_lossy(), &language);
+    }
+
+    /// Note that the terminal gained or lost focus
+    pub fn handle_focus_change(&mut self, focused: bool) {
+        if focused {
+            if self.usage_stats.focused_since.is_none() {
+                self.usage_stats.focused_since = Some(self.time_source.now());
+            }
+        } else {
+            self.take_focused_time();
+            self.usage_stats.focused_since = None;
+        }
+    }
+
+    /// Move the whole seconds focused so far into the pending counts
+    fn take_focused_time(&mut self) {
+        let Some(since) = self.usage_stats.focused_since else {
+            return;
+        };
+        let secs = self.time_source.elapsed_since(since).as_secs();
+        self.usage_stats.focused_since = Some(since + Duration::from_secs(secs));
+        if self.config.stats.enabled {
+            self.usage_stats.pending.record_focus(today(), secs);
+        }
+    }
+
+    /// Write the pending counts if input has paused long enough
+    ///
+    /// Called every tick of the event loop.
+    pub fn flush_usage_stats_when_idle(&mut self) {
+        if !self.config.stats.enabled {
+            return;
+        }
+        let since_flush = self.time_source.elapsed_since(self.usage_stats.last_flush);
+        let since_input = self.time_source.elapsed_since(self.usage_stats.last_input);
+        if since_flush >= FLUSH_INTERVAL && since_input >= IDLE_DELAY {
+            self.flush_usage_stats();
+        }
+    }
+
+    /// Merge the pending counts into the statistics file, dropping days past
+    /// the retention window; called at shutdown
+    pub fn flush_usage_stats(&mut self) {
+        self.usage_stats.last_flush = self.time_source.now();
+        if !self.config.stats.enabled {
+            self.usage_stats.pending = UsageStats::default();
+            return;
+        }
+        self.take_focused_time();
+        if self.usage_stats.pending.is_empty() {
+            return;
+        }
+        let path = self.dir_context.usage_stats_path();
+        let mut stats = UsageStats::load(&path);
+        stats.merge(&self.usage_stats.pending);
+        stats.prune(today(), self.config.stats.retention_days);
+        match stats.save(&path) {
+            Ok(()) => self.usage_stats.pending = UsageStats::default(),
+            Err(e) => tracing::warn!("Failed to save usage statistics: {}", e),
+        }
+    }
+
+    /// The stored counts with the pending ones added
+    fn collected_usage_stats(&mut self) -> UsageStats {
+        self.take_focused_time();
+        let mut stats = UsageStats::load(&self.dir_context.usage_stats_path());
+        stats.merge(&self.usage_stats.pending);
+        stats.prune(today(), self.config.stats.retention_days);
+        stats
+    }
+
+    /// Show the usage statistics in a read-only buffer
+    pub fn show_usage_stats(&mut self) {
+        let stats = self.collected_usage_stats();
+        let existing = self
+            .buffer_metadata
+            .iter()
+            .find(|(_, m)| m.display_name == USAGE_STATS_BUFFER_NAME)
+            .map(|(id, _)| *id);
+        let buffer_id = existing.unwrap_or_else(|| {
+            let buffer_id = self.create_virtual_buffer(
+                USAGE_STATS_BUFFER_NAME.to_string(),
+                "special".to_string(),
+                true,
+            );
+            if let Some(state) = self.buffers.get_mut(&buffer_id) {
+                state.editing_disabled = true;
+                state.margins.set_line_numbers(false);
+            }
+            buffer_id
+        });
+
+        let entries = self.usage_stats_report(&stats);
+        if let Err(e) = self.set_virtual_buffer_content(buffer_id, entries) {
+            tracing::error!("Failed to render usage statistics: {}", e);
+        }
+        self.set_active_buffer(buffer_id);
+    }
+
+    /// Lines of the usage statistics report
+    fn usage_stats_report(&self, stats: &UsageStats) -> Vec<TextPropertyEntry> {
+        let retention_days = self.config.stats.retention_days;
+        let header = |text: String| {
+            TextPropertyEntry::text(format!("{}\n", text)).with_property("type", json!("header"))
+        };
+        let mut entries = vec![header(
+            t!("usage_stats.title", days = retention_days).to_string(),
+        )];
+        if !self.config.stats.enabled {
+            entries.push(TextPropertyEntry::text(format!(
+                "{}\n",
+                t!("usage_stats.disabled")
+            )));
+        }
+        entries.push(TextPropertyEntry::text("\n"));
+        if stats.is_empty() {
+            entries.push(TextPropertyEntry::text(format!(
+                "{}\n",
+                t!("usage_stats.empty")
+            )));
+            return entries;
+        }
+
+        let totals = stats.totals();
+        for line in [
+            t!("usage_stats.keys", count = totals.keys),
+            t!(
+                "usage_stats.focused",
+                time = format_duration(totals.focused_secs)
+            ),
+            t!(
+                "usage_stats.commands",
+                count = totals.commands.values().sum::<u64>()
+            ),
+            t!(
+                "usage_stats.files",
+                count = totals.files.values().sum::<u64>()
+            ),
+        ] {
+            entries.push(TextPropertyEntry::text(format!("{}\n", line)));
+        }
+
+        let today = today();
+        let days = retention_days.clamp(1, SPARKLINE_DAYS);
+        let first = today - chrono::Days::new(days as u64 - 1);
+        entries.push(TextPropertyEntry::text("\n"));
+        entries.push(header(
+            t!(
+                "usage_stats.daily_keys",
+                from = first.format("%Y-%m-%d").to_string(),
+                to = today.format("%Y-%m-%d").to_string()
+            )
+            .to_string(),
+        ));
+        entries.push(TextPropertyEntry::text(format!(
+            "  {}\n",
+            sparkline(&stats.daily_keys(today, days))
+        )));
+
+        let files: BTreeMap<String, u64> = totals
+            .files
+            .iter()
+            .map(|(path, count)| {
+                let display = Path::new(path)
+                    .strip_prefix(&self.working_dir)
+                    .map_or_else(|_| path.clone(), |p| p.to_string_lossy().into_owned());
+                (display, *count)
+            })
+            .collect();
+        for (title, counts) in [
+            (t!("usage_stats.top_commands"), &totals.commands),
+            (t!("usage_stats.top_files"), &files),
+            (t!("usage_stats.top_languages"), &totals.languages),
+        ] {
+            if counts.is_empty() {
+                continue;
+            }
+            entries.push(TextPropertyEntry::text("\n"));
+            entries.push(header(title.to_string()));
+            for (name, count) in top_counts(counts, TOP_ENTRIES) {
+                entries.push(TextPropertyEntry::text(format!(
+                    "  {:>7}  {}\n",
+                    count, name
+                )));
+            }
+        }
+        entries
+    }
+
+    /// Prompt for the file to export the usage statistics to
+    pub fn start_export_usage_stats_prompt(&mut self) {
+        let default = self.working_dir.join(EXPORT_FILE_NAME);
+        self.start_prompt_with_initial_text(
+            t!("usage_stats.export_prompt").to_string(),
+            PromptType::ExportUsageStats,
+            default.to_string_lossy().into_owned(),
+        );
+    }
+
+    /// Write the usage statistics as JSON to `input`, relative to the
+    /// working directory
+    pub(super) fn export_usage_stats(&mut self, input: &str) {
+        let input = input.trim();
+        if input.is_empty() {
+            return;
+        }
+        let path = self.working_dir.join(input);
+        let json = self.collected_usage_stats().to_json_pretty();
+        match std::fs::write(&path, json) {
+            Ok(()) => self.set_status_message(
+                t!("usage_stats.exported", path = path.display().to_string()).to_string(),
+            ),
+            Err(e) => self.set_status_message(
+                t!("usage_stats.export_failed", error = e.to_string()).to_string(),
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_command_name_drops_arguments_and_typing() {
+        assert_eq!(command_name(&Action::Save).as_deref(), Some("Save"));
+        assert_eq!(
+            command_name(&Action::SetBookmark('a')).as_deref(),
+            Some("SetBookmark")
+        );
+        assert_eq!(
+            command_name(&Action::PluginAction("git_log".into())).as_deref(),
+            Some("git_log")
+        );
+        assert_eq!(command_name(&Action::InsertChar('x')), None);
+        assert_eq!(command_name(&Action::None), None);
+    }
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(42), "42s");
+        assert_eq!(format_duration(125), "2m");
+        assert_eq!(format_duration(3 * 3600 + 12 * 60 + 5), "3h 12m");
+    }
+}
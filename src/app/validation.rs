@@ -0,0 +1,81 @@
+//! Opt-in invariant checks of the editor model
+//!
+//! With `FRESH_VALIDATE=1` in the environment (and always in the e2e test
+//! harness), the buffers, cursor sets, overlays, virtual text and split tree
+//! are checked after every key event, plugin command batch and async message.
+//! A violation panics right away, naming the invariant and the ids involved,
+//! so a corruption is reported at the command that caused it instead of at
+//! some later crash.
+
+use super::Editor;
+
+/// Environment variable that enables validation
+pub const VALIDATE_ENV_VAR: &str = "FRESH_VALIDATE";
+
+/// Whether validation was requested through the environment
+pub(super) fn validation_requested() -> bool {
+    std::env::var(VALIDATE_ENV_VAR).is_ok_and(|v| v == "1")
+}
+
+impl Editor {
+    /// Check the editor's invariants after every event
+    pub fn set_validate_invariants(&mut self, enabled: bool) {
+        self.validate_invariants = enabled;
+    }
+
+    /// Validate the model if validation is enabled
+    pub(super) fn validate_if_enabled(&self, after: &str) {
+        if self.validate_invariants {
+            self.validate(after);
+        }
+    }
+
+    /// Panic if the model violates one of its invariants
+    ///
+    /// `after` names what was just processed, for the panic message.
+    pub fn validate(&self, after: &str) {
+        if let Err(e) = self.check_invariants() {
+            panic!("Editor invariant violated after {}: {}", after, e);
+        }
+    }
+
+    fn check_invariants(&self) -> Result<(), String> {
+        for (buffer_id, state) in &self.buffers {
+            state
+                .check_invariants()
+                .map_err(|e| format!("buffer {:?}: {}", buffer_id, e))?;
+        }
+        if !self.buffers.contains_key(&self.active_buffer()) {
+            return Err(format!(
+                "active buffer {:?} is not open",
+                self.active_buffer()
+            ));
+        }
+
+        self.split_manager
+            .check_invariants(|buffer_id| self.buffers.contains_key(&buffer_id))?;
+
+        // The active split's cursors live in its buffer's state while it is
+        // active, so only the other splits keep their own
+        let active_split = self.split_manager.active_split();
+        for split_id in self.split_manager.root().leaf_split_ids() {
+            let Some(view_state) = self.split_view_states.get(&split_id) else {
+                return Err(format!("split {:?} has no view state", split_id));
+            };
+            if split_id == active_split {
+                continue;
+            }
+            let Some(state) = self
+                .split_manager
+                .buffer_for_split(split_id)
+                .and_then(|buffer_id| self.buffers.get(&buffer_id))
+            else {
+                continue;
+            };
+            state
+                .check_cursors(&view_state.cursors)
+                .map_err(|e| format!("split {:?}: {}", split_id, e))?;
+        }
+        Ok(())
+    }
+}
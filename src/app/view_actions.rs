@@ -1,9 +1,13 @@
 //! View mode action handlers.
 //!
-//! This module contains handlers for view-related actions like compose mode toggling.
+//! This module contains handlers for view-related actions like compose mode toggling
+//! and scrolling the view relative to the cursor.
 
 use super::Editor;
+use crate::input::actions::vertical_move_target;
+use crate::model::event::Event;
 use crate::state::ViewMode;
+use crate::view::viewport::LinePlacement;
 use rust_i18n::t;
 
 impl Editor {
@@ -73,4 +77,151 @@ impl Editor {
         };
         self.set_status_message(t!("view.mode", mode = mode_label).to_string());
     }
+
+    /// Scroll the active split so the cursor's line is at the top, middle or
+    /// bottom of the view, without moving the cursor
+    pub fn place_cursor_line(&mut self, placement: LinePlacement) {
+        self.settle_active_viewport_height();
+        let position = self.active_state().cursors.primary().position;
+        let active_split = self.split_manager.active_split();
+        let buffer_id = self.active_buffer();
+        if let (Some(state), Some(view_state)) = (
+            self.buffers.get_mut(&buffer_id),
+            self.split_view_states.get_mut(&active_split),
+        ) {
+            view_state
+                .viewport
+                .place_line(&mut state.buffer, position, placement);
+            view_state.viewport.set_skip_ensure_visible();
+        }
+    }
+
+    /// Scroll the active split by `lines` (negative scrolls up), moving the
+    /// cursor only if it would leave the view
+    pub fn scroll_keeping_cursor(&mut self, lines: isize) {
+        self.settle_active_viewport_height();
+        self.apply_event_to_active_buffer(&Event::Scroll { line_offset: lines });
+        let line_start = self.cursor_line_start();
+        self.move_cursor_into_view(line_start);
+    }
+
+    /// Scroll the active split half a page (negative `direction` scrolls up),
+    /// moving the cursor as many lines so it stays on the same screen row
+    pub fn scroll_half_page(&mut self, direction: isize) {
+        self.settle_active_viewport_height();
+        let lines = (self.active_viewport().visible_line_count() / 2).max(1);
+        self.apply_event_to_active_buffer(&Event::Scroll {
+            line_offset: direction.signum() * lines as isize,
+        });
+
+        let estimated_line_length = self.config.editor.estimated_line_length;
+        let position = self.active_state().cursors.primary().position;
+        let buffer = &mut self.active_state_mut().buffer;
+        let mut iter = buffer.line_iterator(position, estimated_line_length);
+        let mut line_start = iter.current_position();
+        if direction < 0 {
+            for _ in 0..lines {
+                match iter.prev() {
+                    Some((start, _)) => line_start = start,
+                    None => break,
+                }
+            }
+        } else {
+            // Consume the cursor's line
+            iter.next();
+            for _ in 0..lines {
+                match iter.next() {
+                    Some((start, _)) => line_start = start,
+                    None => break,
+                }
+            }
+        }
+        drop(iter);
+        self.move_cursor_into_view(line_start);
+    }
+
+    /// The status bar is hidden while the palette lists commands, so a split
+    /// along the bottom was last laid out a row taller than it is drawn once
+    /// the command runs; give that row back before measuring the view
+    fn settle_active_viewport_height(&mut self) {
+        let status_bar_returns = self.cached_layout.status_bar_area.is_none()
+            && self.prompt.is_none()
+            && self.distraction_free.is_none();
+        let Some(area) = self.cached_layout.editor_content_area else {
+            return;
+        };
+        let active_split = self.split_manager.active_split();
+        let at_bottom = self
+            .cached_layout
+            .split_areas
+            .iter()
+            .any(|(split_id, _, rect, ..)| {
+                *split_id == active_split && rect.bottom() == area.bottom()
+            });
+        if !status_bar_returns || !at_bottom {
+            return;
+        }
+        if let Some(view_state) = self.split_view_states.get_mut(&active_split) {
+            let viewport = &mut view_state.viewport;
+            viewport.resize(viewport.width, viewport.height.saturating_sub(1));
+        }
+    }
+
+    /// Start of the line the primary cursor is on
+    fn cursor_line_start(&mut self) -> usize {
+        let estimated_line_length = self.config.editor.estimated_line_length;
+        let position = self.active_state().cursors.primary().position;
+        self.active_state_mut()
+            .buffer
+            .line_iterator(position, estimated_line_length)
+            .current_position()
+    }
+
+    /// Move the primary cursor to the line starting at `line_start`, or the
+    /// nearest line the view keeps in range without scrolling
+    fn move_cursor_into_view(&mut self, line_start: usize) {
+        let active_split = self.split_manager.active_split();
+        let buffer_id = self.active_buffer();
+        let (first, last) = match (
+            self.buffers.get_mut(&buffer_id),
+            self.split_view_states.get(&active_split),
+        ) {
+            (Some(state), Some(view_state)) => {
+                view_state.viewport.cursor_line_range(&mut state.buffer)
+            }
+            _ => return,
+        };
+        let line_start = line_start.clamp(first, last);
+
+        if line_start != self.cursor_line_start() {
+            let estimated_line_length = self.config.editor.estimated_line_length;
+            let state = self.active_state_mut();
+            let tab_size = state.tab_size.max(1);
+            let cursor_id = state.cursors.primary_id();
+            let cursor = *state.cursors.primary();
+            let (new_position, goal_column) = vertical_move_target(
+                &mut state.buffer,
+                &cursor,
+                line_start,
+                estimated_line_length,
+                tab_size,
+            );
+            let event = Event::MoveCursor {
+                cursor_id,
+                old_position: cursor.position,
+                new_position,
+                old_anchor: cursor.anchor,
+                new_anchor: if cursor.deselect_on_move {
+                    None
+                } else {
+                    cursor.anchor
+                },
+                old_sticky_column: cursor.sticky_column,
+                new_sticky_column: goal_column,
+            };
+            self.apply_event_to_active_buffer(&event);
+        }
+        // Keep the view where it was scrolled to
+        self.active_viewport_mut().set_skip_ensure_visible();
+    }
 }
@@ -0,0 +1,121 @@
+//! Virtual buffer content taken in by chunks
+//!
+//! A plugin panel can hold tens of thousands of entries, such as workspace
+//! diagnostics or project-wide search results. Content a plugin sends goes
+//! into the buffer [`INGEST_CHUNK_ENTRIES`] entries at a time: the first
+//! chunk right away, so the first screen shows at once, then one more chunk
+//! each tick. The text goes into the piece tree and the properties into the
+//! buffer's [`TextPropertyManager`](crate::primitives::text_property::TextPropertyManager),
+//! so rendering and property lookups only ever touch the visible lines.
+//!
+//! Content appended while some is still waiting queues behind it. Any other
+//! plugin command naming the buffer takes in the rest first, so the plugin
+//! always works on the content it sent, and jumping to a far entry never
+//! waits on more than that.
+
+use std::collections::VecDeque;
+
+use super::Editor;
+use crate::model::event::BufferId;
+use crate::primitives::text_property::TextPropertyEntry;
+
+/// Entries taken into a virtual buffer per tick
+pub const INGEST_CHUNK_ENTRIES: usize = 2_000;
+
+impl Editor {
+    /// Set a virtual buffer's content for a plugin, taking in the first
+    /// chunk now and the rest over the next ticks
+    pub fn set_plugin_virtual_buffer_content(
+        &mut self,
+        buffer_id: BufferId,
+        mut entries: Vec<TextPropertyEntry>,
+    ) -> Result<(), String> {
+        // ANSI colors are parsed against the whole content sent before
+        if entries.len() <= INGEST_CHUNK_ENTRIES || self.ansi_entry_caches.contains_key(&buffer_id)
+        {
+            return self.set_virtual_buffer_content(buffer_id, entries);
+        }
+        let rest = entries.split_off(INGEST_CHUNK_ENTRIES);
+        self.set_virtual_buffer_content(buffer_id, entries)?;
+        self.pending_virtual_content
+            .insert(buffer_id, VecDeque::from(rest));
+        Ok(())
+    }
+
+    /// Add `entries` at the end of a virtual buffer's content, after any
+    /// still waiting to be taken in
+    pub fn append_virtual_buffer_content(
+        &mut self,
+        buffer_id: BufferId,
+        entries: Vec<TextPropertyEntry>,
+    ) -> Result<(), String> {
+        if !self.buffers.contains_key(&buffer_id) {
+            return Err("Buffer not found".to_string());
+        }
+        match self.pending_virtual_content.get_mut(&buffer_id) {
+            Some(pending) => {
+                pending.extend(entries);
+                Ok(())
+            }
+            None => self.append_virtual_content_now(buffer_id, entries),
+        }
+    }
+
+    /// Whether some of a virtual buffer's content is still waiting to be
+    /// taken in
+    pub fn has_pending_virtual_content(&self, buffer_id: BufferId) -> bool {
+        self.pending_virtual_content.contains_key(&buffer_id)
+    }
+
+    /// Take in the next chunk of each virtual buffer's waiting content
+    ///
+    /// Returns true if any buffer changed.
+    pub(super) fn ingest_virtual_content(&mut self) -> bool {
+        let buffer_ids: Vec<BufferId> = self.pending_virtual_content.keys().copied().collect();
+        for buffer_id in &buffer_ids {
+            let Some(pending) = self.pending_virtual_content.get_mut(buffer_id) else {
+                continue;
+            };
+            let count = pending.len().min(INGEST_CHUNK_ENTRIES);
+            let chunk: Vec<TextPropertyEntry> = pending.drain(..count).collect();
+            if pending.is_empty() {
+                self.pending_virtual_content.remove(buffer_id);
+            }
+            if let Err(e) = self.append_virtual_content_now(*buffer_id, chunk) {
+                tracing::debug!("Dropping virtual buffer content for {:?}: {}", buffer_id, e);
+                self.pending_virtual_content.remove(buffer_id);
+            }
+        }
+        !buffer_ids.is_empty()
+    }
+
+    /// Take in all of a virtual buffer's waiting content
+    pub(super) fn flush_virtual_content(&mut self, buffer_id: BufferId) {
+        let Some(pending) = self.pending_virtual_content.remove(&buffer_id) else {
+            return;
+        };
+        if let Err(e) = self.append_virtual_content_now(buffer_id, pending.into()) {
+            tracing::debug!("Dropping virtual buffer content for {:?}: {}", buffer_id, e);
+        }
+    }
+
+    /// Add `entries` at the end of a virtual buffer's content
+    fn append_virtual_content_now(
+        &mut self,
+        buffer_id: BufferId,
+        entries: Vec<TextPropertyEntry>,
+    ) -> Result<(), String> {
+        let (entries, ansi_spans) = self.strip_appended_ansi_entries(buffer_id, entries);
+        let end = self
+            .buffers
+            .get(&buffer_id)
+            .ok_or_else(|| "Buffer not found".to_string())?
+            .buffer
+            .len();
+        self.splice_virtual_content_now(buffer_id, end..end, entries)?;
+        if let Some(spans) = ansi_spans {
+            self.add_ansi_spans(buffer_id, &spans);
+        }
+        Ok(())
+    }
+}
@@ -3,6 +3,7 @@
 //! This module provides a generic architecture for different subsystems (LSP, plugins, etc.)
 //! to report warnings with custom status bar indicators and popup content.
 
+use crate::view::glyphs::glyphs;
 use rust_i18n::t;
 use std::path::PathBuf;
 
@@ -88,13 +89,13 @@ impl GeneralWarningDomain {
         Self::default()
     }
 
-    /// Add warnings to the count
-    pub fn add_warnings(&mut self, count: usize) {
+    /// Add warnings to the count, logged at `now`
+    pub fn add_warnings(&mut self, count: usize, now: std::time::Instant) {
         self.count = self.count.saturating_add(count);
         if self.level == WarningLevel::None {
             self.level = WarningLevel::Warning;
         }
-        self.last_update = Some(std::time::Instant::now());
+        self.last_update = Some(now);
     }
 
     /// Clear all warnings
@@ -117,7 +118,7 @@ impl WarningDomain for GeneralWarningDomain {
 
     fn label(&self) -> String {
         if self.count > 0 {
-            format!("[⚠ {}]", self.count)
+            format!("[{} {}]", glyphs().warning, self.count)
         } else {
             String::new()
         }
@@ -211,9 +212,20 @@ impl LspWarningDomain {
             .find(|(_, status)| matches!(status, LspServerStatus::Error))
             .map(|(lang, _)| lang.clone());
 
+        // Otherwise a server that is alive but has stopped answering
+        let unresponsive_lang = statuses
+            .iter()
+            .find(|(_, status)| matches!(status, LspServerStatus::Unresponsive))
+            .map(|(lang, _)| lang.clone());
+
         if let Some(lang) = error_lang {
             self.language = Some(lang);
             self.level = WarningLevel::Error;
+        } else if let Some(lang) = unresponsive_lang {
+            self.language = Some(lang);
+            self.server_command = None;
+            self.error_message = Some(t!("warning.lsp_server_unresponsive").to_string());
+            self.level = WarningLevel::Warning;
         } else {
             self.clear();
         }
@@ -0,0 +1,147 @@
+//! Word completion from open buffers
+//!
+//! Once no input has arrived for [`IDLE_DELAY`], each editor tick gives the
+//! [`WordIndex`] a bounded step to catch up with the open buffers (the active
+//! one first). The completion popup then offers the indexed words that match
+//! the word being typed after the LSP's items, or on their own when the
+//! buffer has no language server.
+//!
+//! Large files, virtual buffers and terminals aren't indexed; with
+//! `editor.word_completion` set to `current_buffer` only the active buffer is.
+
+use crate::config::WordCompletionScope;
+use crate::model::buffer::Buffer;
+use crate::model::event::BufferId;
+use crate::primitives::case_matching::CaseMatching;
+use crate::services::word_index::{WordIndex, WordIndexStats, WorkBudget};
+use rust_i18n::t;
+use std::time::{Duration, Instant};
+
+use super::Editor;
+
+/// Time without input before indexing starts
+const IDLE_DELAY: Duration = Duration::from_millis(200);
+
+/// Work done per idle tick, well within a frame
+const STEP_BUDGET: WorkBudget = WorkBudget {
+    bytes: 64 * 1024,
+    time: Duration::from_millis(4),
+};
+
+/// Most words added to the completion popup
+const MAX_WORD_ITEMS: usize = 50;
+
+/// Word index and the input time it waits on
+pub(crate) struct WordCompletion {
+    index: WordIndex,
+    last_input: Instant,
+}
+
+impl WordCompletion {
+    pub(crate) fn new(now: Instant) -> Self {
+        Self {
+            index: WordIndex::new(),
+            last_input: now,
+        }
+    }
+}
+
+impl Editor {
+    /// Note that input arrived, postponing indexing
+    pub(crate) fn note_input_for_word_index(&mut self) {
+        self.word_completion.last_input = self.time_source.now();
+    }
+
+    /// Index open buffers a step further if the editor is idle
+    ///
+    /// Called every tick of the event loop.
+    pub fn index_words_when_idle(&mut self) {
+        let scope = self.config.editor.word_completion;
+        if scope == WordCompletionScope::Off {
+            self.word_completion.index.clear();
+            return;
+        }
+        let idle = self
+            .time_source
+            .elapsed_since(self.word_completion.last_input);
+        if idle < IDLE_DELAY {
+            return;
+        }
+
+        let active = self.active_buffer();
+        let mut buffers: Vec<(BufferId, &Buffer)> = Vec::new();
+        let ids = std::iter::once(active).chain(
+            self.buffers
+                .keys()
+                .copied()
+                .filter(|id| *id != active && scope == WordCompletionScope::AllBuffers),
+        );
+        for id in ids {
+            if let Some(state) = self.buffers.get(&id) {
+                if self.indexes_words_of(id, &state.buffer) {
+                    buffers.push((id, &state.buffer));
+                }
+            }
+        }
+        self.word_completion
+            .index
+            .step(&buffers, STEP_BUDGET, self.time_source.as_ref());
+    }
+
+    /// Whether a buffer's words are worth indexing
+    fn indexes_words_of(&self, id: BufferId, buffer: &Buffer) -> bool {
+        let virtual_buffer = self
+            .buffer_metadata
+            .get(&id)
+            .is_none_or(|metadata| metadata.is_virtual());
+        !virtual_buffer
+            && !self.terminal_buffers.contains_key(&id)
+            && !buffer.is_large_file()
+            && (buffer.len() as u64) <= self.config.editor.large_file_threshold_bytes
+    }
+
+    /// Size of the word index
+    pub fn word_index_stats(&self) -> WordIndexStats {
+        self.word_completion.index.stats()
+    }
+
+    /// Add indexed words matching `prefix` to LSP completion items
+    ///
+    /// Words the LSP already offers are left out.
+    pub(crate) fn add_word_completions(
+        &self,
+        mut items: Vec<lsp_types::CompletionItem>,
+        prefix: &str,
+    ) -> Vec<lsp_types::CompletionItem> {
+        if self.config.editor.word_completion == WordCompletionScope::Off {
+            return items;
+        }
+        let offered = |word: &str| {
+            items.iter().any(|item| {
+                item.label == word
+                    || item.insert_text.as_deref() == Some(word)
+                    || item.filter_text.as_deref() == Some(word)
+            })
+        };
+        let words: Vec<lsp_types::CompletionItem> = self
+            .word_completion
+            .index
+            .complete(
+                prefix,
+                Some(self.active_buffer()),
+                CaseMatching::from_settings(self.config.search.completion_smart_case, false),
+                MAX_WORD_ITEMS,
+            )
+            .into_iter()
+            .filter(|candidate| !offered(&candidate.word))
+            .map(|candidate| lsp_types::CompletionItem {
+                label: candidate.word,
+                kind: Some(lsp_types::CompletionItemKind::TEXT),
+                detail: Some(t!("lsp.completion_word").to_string()),
+                ..Default::default()
+            })
+            .collect();
+        items.extend(words);
+        items
+    }
+}
@@ -0,0 +1,342 @@
+//! Workspace diagnostics panel
+//!
+//! "Workspace Diagnostics" lists every file a language server published
+//! diagnostics for, with its counts, in a read-only virtual buffer; files
+//! with the most errors come first. Enter on a file shows or hides its
+//! diagnostics, Enter on a diagnostic jumps to it. A publication only redraws
+//! the summary line and the section of the file it was for, so a server
+//! publishing for hundreds of files doesn't rebuild the whole list each time.
+
+use crate::model::event::BufferId;
+use crate::primitives::text_property::TextPropertyEntry;
+use crate::services::lsp::diagnostic_store::{DiagnosticFile, FileDiagnostics, SeverityCounts};
+use lsp_types::DiagnosticSeverity;
+use rust_i18n::t;
+use serde_json::json;
+use std::collections::HashSet;
+use std::ops::Range;
+use std::path::Path;
+
+use super::Editor;
+
+/// Display name of the workspace diagnostics buffer
+pub(crate) const WORKSPACE_DIAGNOSTICS_BUFFER_NAME: &str = "*Workspace Diagnostics*";
+
+/// Buffer mode of the workspace diagnostics buffer (bindings live in
+/// `ModeRegistry::new`)
+pub(crate) const WORKSPACE_DIAGNOSTICS_MODE: &str = "workspace-diagnostics";
+
+/// An open workspace diagnostics panel
+pub(crate) struct WorkspaceDiagnosticsPanel {
+    buffer_id: BufferId,
+    /// Byte length of the summary lines at the top
+    header_len: usize,
+    /// Files in the order listed, with the byte length of their section
+    sections: Vec<(DiagnosticFile, usize)>,
+    /// Files listed with their diagnostics
+    expanded: HashSet<DiagnosticFile>,
+}
+
+impl WorkspaceDiagnosticsPanel {
+    /// Byte offset of the section at `index`
+    fn section_start(&self, index: usize) -> usize {
+        self.header_len
+            + self.sections[..index]
+                .iter()
+                .map(|(_, len)| len)
+                .sum::<usize>()
+    }
+
+    /// Index of the section containing byte `position`
+    fn section_at(&self, position: usize) -> Option<usize> {
+        let mut start = self.header_len;
+        for (index, (_, len)) in self.sections.iter().enumerate() {
+            if position < start + len {
+                return (position >= start).then_some(index);
+            }
+            start += len;
+        }
+        None
+    }
+}
+
+fn counts_text(counts: &SeverityCounts) -> String {
+    format!(
+        "E:{} W:{} I:{}",
+        counts.errors,
+        counts.warnings,
+        counts.infos + counts.hints
+    )
+}
+
+fn entries_len(entries: &[TextPropertyEntry]) -> usize {
+    entries.iter().map(|entry| entry.text.len()).sum()
+}
+
+/// Summary lines at the top of the panel
+fn header_entries(total: SeverityCounts, files: usize) -> Vec<TextPropertyEntry> {
+    let mut entries = vec![
+        TextPropertyEntry::text(format!(
+            "{}\n",
+            t!(
+                "workspace_diagnostics.title",
+                counts = counts_text(&total),
+                files = files
+            )
+        ))
+        .with_property("type", json!("header")),
+        TextPropertyEntry::text(format!("{}\n\n", t!("workspace_diagnostics.help")))
+            .with_property("type", json!("help")),
+    ];
+    if files == 0 {
+        entries.push(TextPropertyEntry::text(format!(
+            "  {}\n",
+            t!("workspace_diagnostics.empty")
+        )));
+    }
+    entries
+}
+
+/// Lines of one file: its counts, then its diagnostics by position when
+/// expanded
+fn section_entries(
+    file: &DiagnosticFile,
+    entry: &FileDiagnostics,
+    root: &Path,
+    expanded: bool,
+) -> Vec<TextPropertyEntry> {
+    let name = match file.path() {
+        Some(path) => path
+            .strip_prefix(root)
+            .unwrap_or(path)
+            .display()
+            .to_string(),
+        None => file.to_string(),
+    };
+    let marker = if expanded { "▾" } else { "▸" };
+    let mut entries = vec![TextPropertyEntry::text(format!(
+        "{} {}  {}\n",
+        marker,
+        name,
+        counts_text(&entry.counts)
+    ))
+    .with_property("type", json!("file"))];
+    if !expanded {
+        return entries;
+    }
+
+    let mut diagnostics: Vec<_> = entry.diagnostics.iter().collect();
+    diagnostics.sort_by_key(|d| (d.range.start.line, d.range.start.character));
+    for diagnostic in diagnostics {
+        let line = diagnostic.range.start.line as usize + 1;
+        let column = diagnostic.range.start.character as usize + 1;
+        let severity = match diagnostic.severity {
+            Some(DiagnosticSeverity::ERROR) => "E",
+            Some(DiagnosticSeverity::WARNING) => "W",
+            Some(DiagnosticSeverity::INFORMATION) => "I",
+            _ => "H",
+        };
+        let source = diagnostic
+            .source
+            .as_ref()
+            .map(|source| format!(" ({})", source))
+            .unwrap_or_default();
+        entries.push(
+            TextPropertyEntry::text(format!(
+                "    {:>8}  {}  {}{}\n",
+                format!("{}:{}", line, column),
+                severity,
+                diagnostic.message.lines().next().unwrap_or_default(),
+                source
+            ))
+            .with_property("type", json!("item"))
+            .with_property("line", json!(line))
+            .with_property("column", json!(column)),
+        );
+    }
+    entries
+}
+
+impl Editor {
+    /// Open the workspace diagnostics panel, or switch to it
+    pub fn open_workspace_diagnostics(&mut self) {
+        if let Some(panel) = &self.workspace_diagnostics {
+            let buffer_id = panel.buffer_id;
+            self.set_active_buffer(buffer_id);
+            return;
+        }
+
+        let buffer_id = self.create_virtual_buffer(
+            WORKSPACE_DIAGNOSTICS_BUFFER_NAME.to_string(),
+            WORKSPACE_DIAGNOSTICS_MODE.to_string(),
+            true,
+        );
+        if let Some(state) = self.buffers.get_mut(&buffer_id) {
+            state.editing_disabled = true;
+            state.margins.set_line_numbers(false);
+        }
+        self.workspace_diagnostics = Some(WorkspaceDiagnosticsPanel {
+            buffer_id,
+            header_len: 0,
+            sections: Vec::new(),
+            expanded: HashSet::new(),
+        });
+        self.render_workspace_diagnostics();
+        self.set_active_buffer(buffer_id);
+    }
+
+    /// Render the whole panel from the diagnostics store
+    fn render_workspace_diagnostics(&mut self) {
+        let Some(panel) = self.workspace_diagnostics.as_mut() else {
+            return;
+        };
+        let store = &self.stored_diagnostics;
+        let summary = store.summary();
+        let mut entries = header_entries(summary.total, summary.files.len());
+        panel.header_len = entries_len(&entries);
+        panel.sections.clear();
+        for file in &summary.files {
+            let Some(entry) = store.get_file(&file.file) else {
+                continue;
+            };
+            let expanded = panel.expanded.contains(&file.file);
+            let section = section_entries(&file.file, entry, &self.working_dir, expanded);
+            panel
+                .sections
+                .push((file.file.clone(), entries_len(&section)));
+            entries.extend(section);
+        }
+        let buffer_id = panel.buffer_id;
+        if let Err(e) = self.set_virtual_buffer_content(buffer_id, entries) {
+            tracing::error!("Failed to render workspace diagnostics: {}", e);
+        }
+    }
+
+    /// Redraw the summary and the section of `file` after diagnostics were
+    /// published for it, moving the section to its new place in the order
+    pub(super) fn refresh_workspace_diagnostics_file(&mut self, file: &DiagnosticFile) {
+        let Some(panel) = self.workspace_diagnostics.as_mut() else {
+            return;
+        };
+        let store = &self.stored_diagnostics;
+        // Each splice's range is taken after the ones before it are applied
+        let mut splices: Vec<(Range<usize>, Vec<TextPropertyEntry>)> = Vec::new();
+
+        let header = header_entries(store.total(), store.len());
+        splices.push((0..panel.header_len, header));
+        panel.header_len = entries_len(&splices[0].1);
+
+        if let Some(index) = panel.sections.iter().position(|(f, _)| f == file) {
+            let start = panel.section_start(index);
+            let (_, len) = panel.sections.remove(index);
+            splices.push((start..start + len, Vec::new()));
+        }
+
+        match store.get_file(file) {
+            Some(entry) => {
+                let key = (entry.counts.rank(), file);
+                let index = panel
+                    .sections
+                    .iter()
+                    .position(|(other, _)| {
+                        store
+                            .get_file(other)
+                            .is_some_and(|e| (e.counts.rank(), other) > key)
+                    })
+                    .unwrap_or(panel.sections.len());
+                let start = panel.section_start(index);
+                let expanded = panel.expanded.contains(file);
+                let section = section_entries(file, entry, &self.working_dir, expanded);
+                panel
+                    .sections
+                    .insert(index, (file.clone(), entries_len(&section)));
+                splices.push((start..start, section));
+            }
+            None => {
+                panel.expanded.remove(file);
+            }
+        }
+
+        let buffer_id = panel.buffer_id;
+        for (range, entries) in splices {
+            if let Err(e) = self.splice_virtual_buffer_content(buffer_id, range, entries) {
+                tracing::error!("Failed to update workspace diagnostics: {}", e);
+                self.render_workspace_diagnostics();
+                return;
+            }
+        }
+    }
+
+    /// Show or hide the diagnostics of the file under the cursor, or jump to
+    /// the diagnostic under it
+    pub(super) fn workspace_diagnostics_open(&mut self) {
+        let Some(panel) = &self.workspace_diagnostics else {
+            return;
+        };
+        if self.active_buffer() != panel.buffer_id {
+            return;
+        }
+        let position = self.active_state().cursors.primary().position;
+        let Some(index) = panel.section_at(position) else {
+            return;
+        };
+        let file = panel.sections[index].0.clone();
+
+        let target = self.get_text_properties_at_cursor().and_then(|properties| {
+            properties.into_iter().find_map(|p| {
+                let line = p.get("line")?.as_u64()?;
+                let column = p.get("column").and_then(|v| v.as_u64()).unwrap_or(1);
+                Some((line as usize, column as usize))
+            })
+        });
+        let Some((line, column)) = target else {
+            self.toggle_workspace_diagnostics_file(index);
+            return;
+        };
+        let Some(path) = file.path().map(Path::to_path_buf) else {
+            self.set_status_message(
+                t!("workspace_diagnostics.not_a_file", uri = file.to_string()).to_string(),
+            );
+            return;
+        };
+        match self.open_file(&path) {
+            Ok(_) => self.goto_line_col(line, Some(column)),
+            Err(e) => {
+                self.set_status_message(t!("file.error_opening", error = e.to_string()).to_string())
+            }
+        }
+    }
+
+    /// Show or hide the diagnostics of the file at section `index`
+    fn toggle_workspace_diagnostics_file(&mut self, index: usize) {
+        let Some(panel) = self.workspace_diagnostics.as_mut() else {
+            return;
+        };
+        let (file, len) = panel.sections[index].clone();
+        let expanded = !panel.expanded.remove(&file);
+        if expanded {
+            panel.expanded.insert(file.clone());
+        }
+        let Some(entry) = self.stored_diagnostics.get_file(&file) else {
+            return;
+        };
+        let start = panel.section_start(index);
+        let section = section_entries(&file, entry, &self.working_dir, expanded);
+        panel.sections[index].1 = entries_len(&section);
+        let buffer_id = panel.buffer_id;
+        if let Err(e) = self.splice_virtual_buffer_content(buffer_id, start..start + len, section) {
+            tracing::error!("Failed to update workspace diagnostics: {}", e);
+        }
+    }
+
+    /// Forget the panel when its buffer is closed
+    pub(super) fn on_workspace_diagnostics_buffer_closed(&mut self, buffer_id: BufferId) {
+        if self
+            .workspace_diagnostics
+            .as_ref()
+            .is_some_and(|panel| panel.buffer_id == buffer_id)
+        {
+            self.workspace_diagnostics = None;
+        }
+    }
+}
@@ -1,4 +1,4 @@
-use crate::types::{context_keys, LspServerConfig, ProcessLimits};
+use crate::types::{context_keys, LspLanguageConfig, LspServerConfig, ProcessLimits};
 
 use rust_i18n::t;
 use schemars::JsonSchema;
@@ -15,8 +15,24 @@ pub struct ThemeName(pub String);
 
 impl ThemeName {
     /// Built-in theme options shown in the settings dropdown
-    pub const BUILTIN_OPTIONS: &'static [&'static str] =
-        &["dark", "light", "high-contrast", "nostalgia"];
+    pub const BUILTIN_OPTIONS: &'static [&'static str] = &[
+        "dark",
+        "light",
+        "high-contrast",
+        "nostalgia",
+        "dark-high-contrast",
+        "light-high-contrast",
+        Self::AUTO,
+    ];
+
+    /// Follow the terminal's background with `ui.light_theme` or
+    /// `ui.dark_theme`
+    pub const AUTO: &'static str = "auto";
+
+    /// Whether this is [`Self::AUTO`]
+    pub fn is_auto(&self) -> bool {
+        self.0 == Self::AUTO
+    }
 }
 
 impl Deref for ThemeName {
@@ -317,7 +333,8 @@ pub struct Config {
     #[serde(default)]
     pub version: u32,
 
-    /// Color theme name
+    /// Color theme name. "auto" follows the terminal: `ui.light_theme`
+    /// on a light background, `ui.dark_theme` on a dark one.
     #[serde(default = "default_theme_name")]
     pub theme: ThemeName,
 
@@ -346,6 +363,10 @@ pub struct Config {
     #[serde(default)]
     pub terminal: TerminalConfig,
 
+    /// Mouse settings (scroll amounts, multi-cursor modifier)
+    #[serde(default)]
+    pub mouse: MouseConfig,
+
     /// Custom keybindings (overrides for the active map)
     #[serde(default)]
     pub keybindings: Vec<Keybinding>,
@@ -363,13 +384,58 @@ pub struct Config {
     #[serde(default)]
     pub languages: HashMap<String, LanguageConfig>,
 
-    /// LSP server configurations by language
+    /// LSP server configurations by language: one server, or a list of
+    /// servers that all attach to the language's buffers
     #[serde(default)]
-    pub lsp: HashMap<String, LspServerConfig>,
+    pub lsp: HashMap<String, LspLanguageConfig>,
 
     /// Warning notification settings
     #[serde(default)]
     pub warnings: WarningsConfig,
+
+    /// TODO/FIXME comment index and highlighting
+    #[serde(default)]
+    pub todos: TodosConfig,
+
+    /// Clipboard history used by "Paste from History" and cycle paste
+    #[serde(default)]
+    pub clipboard: ClipboardConfig,
+
+    /// Reloading of open files that change on disk
+    #[serde(default)]
+    pub files: FilesConfig,
+
+    /// Distraction-free writing mode
+    #[serde(default)]
+    pub distraction_free: DistractionFreeConfig,
+
+    /// Case matching of search, replace and completion
+    #[serde(default)]
+    pub search: SearchConfig,
+
+    /// Session snapshots kept for "Restore Session from Snapshot"
+    #[serde(default)]
+    pub session: SessionSnapshotConfig,
+
+    /// Installing new releases from inside the editor
+    #[serde(default)]
+    pub updates: UpdatesConfig,
+
+    /// Local usage statistics shown by "Usage Statistics"
+    #[serde(default)]
+    pub stats: StatsConfig,
+
+    /// Size limits of the editor's own log file
+    #[serde(default)]
+    pub log: LogConfig,
+
+    /// Memory the editor's caches may take together, and compaction of idle buffers
+    #[serde(default)]
+    pub performance: PerformanceConfig,
+
+    /// User interface settings (terminal cursor shape, whitespace rendering)
+    #[serde(default)]
+    pub ui: UiConfig,
 }
 
 fn default_keybinding_map_name() -> KeybindingMapName {
@@ -386,6 +452,26 @@ fn default_theme_name() -> ThemeName {
     ThemeName("high-contrast".to_string())
 }
 
+/// JSON Schema of the config file, as in `plugins/config-schema.json`
+///
+/// Printed by `generate_schema` and `fresh --dump-schema`.
+pub fn config_schema() -> serde_json::Value {
+    let schema = schemars::schema_for!(Config);
+    let mut json: serde_json::Value =
+        serde_json::to_value(&schema).expect("Failed to serialize schema");
+
+    // Remove the default value for menu - it's too large and the schema
+    // is for validation, not for storing defaults
+    if let Some(properties) = json.get_mut("properties") {
+        if let Some(menu) = properties.get_mut("menu") {
+            if let Some(obj) = menu.as_object_mut() {
+                obj.remove("default");
+            }
+        }
+    }
+    json
+}
+
 /// Editor behavior configuration
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct EditorConfig {
@@ -405,6 +491,18 @@ pub struct EditorConfig {
     #[serde(default = "default_false")]
     pub relative_line_numbers: bool,
 
+    /// Gutter columns from left to right: "indicators" (diagnostics, git
+    /// changes), "line_numbers" and the names of columns added by plugins.
+    /// Built-in columns left out are hidden; plugin columns left out are
+    /// shown left of the others.
+    #[serde(default = "default_gutter_columns")]
+    pub gutter_columns: Vec<String>,
+
+    /// Width in cells of gutter columns by name, replacing the column's own
+    /// width. For "line_numbers" it is the minimum width. 0 hides a column.
+    #[serde(default)]
+    pub gutter_column_widths: HashMap<String, usize>,
+
     /// Minimum lines to keep visible above/below cursor when scrolling
     #[serde(default = "default_scroll_offset")]
     pub scroll_offset: usize,
@@ -495,6 +593,14 @@ pub struct EditorConfig {
     #[serde(default = "default_file_tree_poll_interval")]
     pub file_tree_poll_interval_ms: u64,
 
+    /// Seconds a buffer created by a plugin may stay out of every split,
+    /// untouched by its plugin, before it is closed.
+    /// Plugins opt a buffer out when they keep it hidden on purpose.
+    /// Set to 0 to keep such buffers until their plugin closes them.
+    /// Default: 600 seconds (10 minutes)
+    #[serde(default = "default_plugin_buffer_idle_close")]
+    pub plugin_buffer_idle_close_secs: u64,
+
     /// Default line ending format for new files.
     /// Files loaded from disk will use their detected line ending format.
     /// Options: "lf" (Unix/Linux/macOS), "crlf" (Windows), "cr" (Classic Mac)
@@ -507,6 +613,82 @@ pub struct EditorConfig {
     /// Default: blinking_block
     #[serde(default)]
     pub cursor_style: CursorStyle,
+
+    /// Display width of East Asian "ambiguous" characters (box drawing, circled digits, ...).
+    /// CJK terminals usually render them two cells wide; set this to match your terminal.
+    /// Options: "narrow" (1 cell), "wide" (2 cells)
+    /// Default: narrow
+    #[serde(default)]
+    pub ambiguous_width: AmbiguousWidth,
+
+    /// Where "Toggle Inline Blame" shows the author, date and summary of a line.
+    /// Options: "line_above" (a virtual line above it), "end_of_line" (after its text)
+    /// Default: line_above
+    #[serde(default)]
+    pub inline_blame_position: InlineBlamePosition,
+
+    /// Words from open buffers offered by the completion popup, indexed while idle.
+    /// Options: "all_buffers", "current_buffer", "off"
+    /// Default: all_buffers
+    #[serde(default)]
+    pub word_completion: WordCompletionScope,
+
+    /// Abbreviations whose final period doesn't end a sentence for the
+    /// sentence motions, compared ignoring case.
+    #[serde(default = "default_sentence_abbreviations")]
+    pub sentence_abbreviations: Vec<String>,
+
+    /// Open the completion popup by itself while typing: after a pause in typing a word,
+    /// or right away after a trigger character such as "." or "::".
+    /// Can be turned off per language with `auto_complete` in the language config.
+    /// Default: false
+    #[serde(default = "default_false")]
+    pub auto_complete: bool,
+
+    /// Pause in typing, in milliseconds, before the completion popup opens by itself.
+    /// Default: 150ms
+    #[serde(default = "default_auto_complete_delay")]
+    pub auto_complete_delay_ms: u64,
+
+    /// Make undo skip over edits made by plugins, undoing them together with the
+    /// user edit they followed so Undo always reverts your own last action.
+    /// When off, undo walks back through every edit in the order it happened.
+    /// Default: false
+    #[serde(default = "default_false")]
+    pub undo_skips_plugin_edits: bool,
+
+    /// Where to show the key path at the cursor (`spec.containers[3].name`)
+    /// in JSON, YAML and TOML files.
+    /// Options: "status_bar", "winbar" (a line above each split), "off"
+    /// Default: status_bar
+    #[serde(default)]
+    pub data_path_breadcrumb: DataPathBreadcrumb,
+
+    /// How key paths are written in the breadcrumb and when copied.
+    /// Options: "dotted" (`a.b[3]["odd key"]`), "jq" (`.a.b[3]."odd key"`)
+    /// Default: dotted
+    #[serde(default)]
+    pub data_path_style: DataPathStyle,
+
+    /// Largest range in bytes a plugin can read from a buffer in one call.
+    /// Larger reads fail and tell the plugin to read smaller ranges or use
+    /// `readBufferChunks`, so a plugin cannot copy a huge file at once.
+    /// Default: 16777216 (16 MiB)
+    #[serde(default = "default_plugin_text_limit")]
+    pub plugin_text_limit_bytes: u64,
+
+    /// Deepest chain of plugin hooks set off by plugin commands, such as a
+    /// handler of text changes that inserts text. Handlers deeper than this
+    /// are cut off until the next editor event, with a warning naming them.
+    /// Default: 8
+    #[serde(default = "default_plugin_hook_chain_depth")]
+    pub plugin_hook_chain_depth: u32,
+
+    /// Most times one plugin's handler of a hook can run for one buffer
+    /// within a chain set off by plugin commands before it is cut off.
+    /// Default: 256
+    #[serde(default = "default_plugin_hook_repeat_limit")]
+    pub plugin_hook_repeat_limit: u32,
 }
 
 fn default_tab_size() -> usize {
@@ -522,6 +704,21 @@ fn default_large_file_threshold() -> u64 {
     LARGE_FILE_THRESHOLD_BYTES
 }
 
+/// Default largest buffer range a plugin can read in one call
+pub const PLUGIN_TEXT_LIMIT_BYTES: u64 = 16 * 1024 * 1024; // 16MB
+
+fn default_plugin_text_limit() -> u64 {
+    PLUGIN_TEXT_LIMIT_BYTES
+}
+
+fn default_plugin_hook_chain_depth() -> u32 {
+    crate::services::plugins::hook_guard::DEFAULT_HOOK_CHAIN_DEPTH
+}
+
+fn default_plugin_hook_repeat_limit() -> u32 {
+    crate::services::plugins::hook_guard::DEFAULT_HOOK_REPEAT_LIMIT
+}
+
 fn default_true() -> bool {
     true
 }
@@ -530,6 +727,20 @@ fn default_false() -> bool {
     false
 }
 
+fn default_gutter_columns() -> Vec<String> {
+    vec!["indicators".to_string(), "line_numbers".to_string()]
+}
+
+fn default_sentence_abbreviations() -> Vec<String> {
+    [
+        "e.g.", "i.e.", "vs.", "cf.", "approx.", "Mr.", "Mrs.", "Ms.", "Dr.", "Prof.", "St.",
+        "Jr.", "Sr.", "No.", "Fig.",
+    ]
+    .iter()
+    .map(|s| s.to_string())
+    .collect()
+}
+
 fn default_scroll_offset() -> usize {
     3
 }
@@ -558,6 +769,10 @@ fn default_mouse_hover_delay() -> u64 {
     500 // 500ms delay before showing hover info
 }
 
+fn default_auto_complete_delay() -> u64 {
+    150 // 150ms without typing before completion opens by itself
+}
+
 fn default_double_click_time() -> u64 {
     500 // 500ms window for detecting double-clicks
 }
@@ -570,6 +785,10 @@ fn default_file_tree_poll_interval() -> u64 {
     3000 // 3 seconds between directory mtime checks
 }
 
+fn default_plugin_buffer_idle_close() -> u64 {
+    600 // 10 minutes hidden and untouched
+}
+
 impl Default for EditorConfig {
     fn default() -> Self {
         Self {
@@ -577,6 +796,8 @@ impl Default for EditorConfig {
             auto_indent: true,
             line_numbers: true,
             relative_line_numbers: false,
+            gutter_columns: default_gutter_columns(),
+            gutter_column_widths: HashMap::new(),
             scroll_offset: default_scroll_offset(),
             syntax_highlighting: true,
             line_wrap: true,
@@ -593,8 +814,21 @@ impl Default for EditorConfig {
             double_click_time_ms: default_double_click_time(),
             auto_revert_poll_interval_ms: default_auto_revert_poll_interval(),
             file_tree_poll_interval_ms: default_file_tree_poll_interval(),
+            plugin_buffer_idle_close_secs: default_plugin_buffer_idle_close(),
             default_line_ending: LineEndingOption::default(),
             cursor_style: CursorStyle::default(),
+            ambiguous_width: AmbiguousWidth::default(),
+            inline_blame_position: InlineBlamePosition::default(),
+            word_completion: WordCompletionScope::default(),
+            sentence_abbreviations: default_sentence_abbreviations(),
+            auto_complete: false,
+            auto_complete_delay_ms: default_auto_complete_delay(),
+            undo_skips_plugin_edits: false,
+            data_path_breadcrumb: DataPathBreadcrumb::default(),
+            data_path_style: DataPathStyle::default(),
+            plugin_text_limit_bytes: default_plugin_text_limit(),
+            plugin_hook_chain_depth: default_plugin_hook_chain_depth(),
+            plugin_hook_repeat_limit: default_plugin_hook_repeat_limit(),
         }
     }
 }
@@ -621,12 +855,38 @@ pub struct FileExplorerConfig {
     /// Width of file explorer as percentage (0.0 to 1.0)
     #[serde(default = "default_explorer_width")]
     pub width: f32,
+
+    /// Maximum number of entries shown per directory before the rest are
+    /// hidden behind a "load more" row (0 = no limit)
+    #[serde(default = "default_explorer_max_visible_entries")]
+    pub max_visible_entries: usize,
+
+    /// Show the number of entries next to collapsed directories
+    #[serde(default = "default_false")]
+    pub show_entry_counts: bool,
+
+    /// Show size and modified time columns next to entries
+    #[serde(default = "default_false")]
+    pub show_details: bool,
+
+    /// Milliseconds changes on disk are collected before the tree is
+    /// updated with all of them at once
+    #[serde(default = "default_explorer_refresh_debounce")]
+    pub refresh_debounce_ms: u64,
 }
 
 fn default_explorer_width() -> f32 {
     0.3 // 30% of screen width
 }
 
+fn default_explorer_max_visible_entries() -> usize {
+    10_000
+}
+
+fn default_explorer_refresh_debounce() -> u64 {
+    200
+}
+
 /// Terminal configuration
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct TerminalConfig {
@@ -644,6 +904,53 @@ impl Default for TerminalConfig {
     }
 }
 
+/// Mouse configuration
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct MouseConfig {
+    /// Number of lines scrolled per mouse wheel step (default: 3)
+    #[serde(default = "default_scroll_lines")]
+    pub scroll_lines: usize,
+
+    /// Number of columns scrolled per horizontal wheel or trackpad step (default: 4)
+    #[serde(default = "default_horizontal_scroll_columns")]
+    pub horizontal_scroll_columns: usize,
+
+    /// Modifier that adds or removes a cursor on click (default: ctrl)
+    /// The other of Ctrl and Alt places one cursor per line when dragging
+    /// up or down. Options: "ctrl", "alt"
+    #[serde(default)]
+    pub multi_cursor_modifier: MultiCursorModifier,
+}
+
+fn default_scroll_lines() -> usize {
+    3
+}
+
+fn default_horizontal_scroll_columns() -> usize {
+    4
+}
+
+impl Default for MouseConfig {
+    fn default() -> Self {
+        Self {
+            scroll_lines: default_scroll_lines(),
+            horizontal_scroll_columns: default_horizontal_scroll_columns(),
+            multi_cursor_modifier: MultiCursorModifier::default(),
+        }
+    }
+}
+
+/// Modifier held while clicking to add or remove cursors
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum MultiCursorModifier {
+    /// Ctrl+Click adds a cursor, Alt+Drag places a column of cursors
+    #[default]
+    Ctrl,
+    /// Alt+Click adds a cursor, Ctrl+Drag places a column of cursors
+    Alt,
+}
+
 /// Warning notification configuration
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct WarningsConfig {
@@ -661,6 +968,586 @@ impl Default for WarningsConfig {
     }
 }
 
+/// TODO/FIXME comment configuration
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct TodosConfig {
+    /// Highlight the keywords inside comments of open buffers
+    #[serde(default = "default_true")]
+    pub highlight: bool,
+
+    /// Keywords to look for, in the order they are listed, with their highlight colors.
+    /// A keyword may be followed by an author in parentheses, as in `TODO(alice):`
+    #[serde(default = "default_todo_keywords")]
+    pub keywords: Vec<TodoKeyword>,
+}
+
+/// A TODO-style keyword and its highlight color
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct TodoKeyword {
+    /// The keyword, matched case-sensitively as a whole word
+    pub word: String,
+
+    /// Highlight color as [r, g, b]
+    pub color: [u8; 3],
+}
+
+impl TodoKeyword {
+    fn new(word: &str, color: [u8; 3]) -> Self {
+        Self {
+            word: word.to_string(),
+            color,
+        }
+    }
+}
+
+fn default_todo_keywords() -> Vec<TodoKeyword> {
+    vec![
+        TodoKeyword::new("TODO", [255, 200, 50]),
+        TodoKeyword::new("FIXME", [255, 100, 100]),
+        TodoKeyword::new("HACK", [200, 100, 255]),
+        TodoKeyword::new("XXX", [255, 150, 50]),
+    ]
+}
+
+impl Default for TodosConfig {
+    fn default() -> Self {
+        Self {
+            highlight: true,
+            keywords: default_todo_keywords(),
+        }
+    }
+}
+
+/// Clipboard history configuration
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct ClipboardConfig {
+    /// Number of copied texts kept in the clipboard history
+    #[serde(default = "default_clipboard_history_size")]
+    pub history_size: usize,
+
+    /// Total size in bytes of the texts kept in the clipboard history.
+    /// A single copy larger than this is not added to the history.
+    #[serde(default = "default_clipboard_history_max_bytes")]
+    pub history_max_bytes: usize,
+
+    /// Also add text pasted from outside the editor to the clipboard history
+    #[serde(default = "default_false")]
+    pub record_external_pastes: bool,
+
+    /// Save the clipboard history with the session.
+    /// Off by default since copied text may be private.
+    #[serde(default = "default_false")]
+    pub save_history_in_session: bool,
+
+    /// Reindent pasted code to fit where it's pasted, as "Paste and Reindent"
+    /// does, in buffers with syntax highlighting. "Paste Verbatim" still
+    /// pastes text as it is.
+    #[serde(default = "default_false")]
+    pub reindent_pastes: bool,
+
+    /// Milliseconds to wait for the system clipboard before falling back to
+    /// the terminal (OSC 52) and the editor's own clipboard. A clipboard that
+    /// doesn't answer in time is skipped for a while; "Retry System Clipboard"
+    /// uses it again right away. 0 waits without limit.
+    #[serde(default = "default_clipboard_system_timeout_ms")]
+    pub system_timeout_ms: u64,
+}
+
+fn default_clipboard_history_size() -> usize {
+    10
+}
+
+fn default_clipboard_history_max_bytes() -> usize {
+    16 * 1024 * 1024
+}
+
+fn default_clipboard_system_timeout_ms() -> u64 {
+    500
+}
+
+impl Default for ClipboardConfig {
+    fn default() -> Self {
+        Self {
+            history_size: default_clipboard_history_size(),
+            history_max_bytes: default_clipboard_history_max_bytes(),
+            record_external_pastes: false,
+            save_history_in_session: false,
+            reindent_pastes: false,
+            system_timeout_ms: default_clipboard_system_timeout_ms(),
+        }
+    }
+}
+
+/// Configuration for open files that change on disk
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct FilesConfig {
+    /// What to do when an open file without unsaved changes is modified by
+    /// another program: reload it ("on"), leave it ("off") or ask first ("ask").
+    /// "Toggle Auto-Revert for Buffer" overrides this for a single buffer.
+    #[serde(default)]
+    pub auto_revert: AutoRevertMode,
+
+    /// Glob patterns (gitignore syntax, relative to the working directory) of
+    /// files that are never checked for changes on disk, e.g. "*.log" or "build/"
+    #[serde(default)]
+    pub auto_revert_exclude: Vec<String>,
+
+    /// Command that writes its standard input to the file named by its last
+    /// argument with elevated rights, e.g. ["sudo", "-n", "tee"]. When set,
+    /// a save that fails for lack of permission offers "Retry with sudo",
+    /// which runs it after asking. Unix only; empty leaves the option out.
+    #[serde(default)]
+    pub sudo_save_command: Vec<String>,
+
+    /// Extra arguments for every `ssh` run to open, save or list
+    /// `ssh://host/path` locations, e.g. ["-F", "~/.ssh/work_config"].
+    /// They come before the editor's own options, so they take precedence.
+    #[serde(default)]
+    pub ssh_args: Vec<String>,
+
+    /// Share one ssh connection per host between the commands that open,
+    /// save and list remote files, so only the first pays for logging in
+    #[serde(default = "default_true")]
+    pub ssh_control_master: bool,
+
+    /// What opening a file that doesn't exist does: open an empty buffer that
+    /// saving creates the file from ("create"), or ask first ("ask")
+    #[serde(default)]
+    pub missing_files: MissingFileMode,
+
+    /// Glob patterns (gitignore syntax, matched against the file's canonical
+    /// path from the root) of files that open read-only until "Unlock
+    /// Protected File" is run, e.g. "**/.env*" or "/etc/**". The lists of all
+    /// config layers add up, the built-in ones first; a "!" pattern lifts the
+    /// protection an earlier one gave.
+    #[serde(default = "default_protected_globs")]
+    pub protected_globs: Vec<String>,
+}
+
+fn default_protected_globs() -> Vec<String> {
+    [
+        // System files, but not the home directories some systems keep in /usr
+        "/etc/**",
+        "/private/etc/**",
+        "/boot/**",
+        "/usr/**",
+        "!/usr/home/**",
+        // Credentials, wherever they are
+        "**/.env*",
+        "!**/.env.example",
+        "!**/.env.sample",
+        "**/id_rsa*",
+        "**/id_ecdsa*",
+        "**/id_ed25519*",
+        "**/.aws/credentials",
+        "**/.netrc",
+        "**/.pgpass",
+    ]
+    .iter()
+    .map(|pattern| pattern.to_string())
+    .collect()
+}
+
+impl Default for FilesConfig {
+    fn default() -> Self {
+        Self {
+            auto_revert: AutoRevertMode::On,
+            auto_revert_exclude: Vec::new(),
+            sudo_save_command: Vec::new(),
+            ssh_args: Vec::new(),
+            ssh_control_master: true,
+            missing_files: MissingFileMode::Create,
+            protected_globs: default_protected_globs(),
+        }
+    }
+}
+
+/// Distraction-free mode configuration
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct DistractionFreeConfig {
+    /// Widest the text column gets, in characters; the column is centered
+    /// with equal padding on both sides
+    #[serde(default = "default_distraction_free_width")]
+    pub width: u16,
+
+    /// Dim every line except the paragraph the cursor is in
+    #[serde(default = "default_false")]
+    pub dim_other_paragraphs: bool,
+
+    /// Save the mode with the session and turn it back on when the session
+    /// is restored. Off by default, so a restart brings back the normal layout.
+    #[serde(default = "default_false")]
+    pub restore_with_session: bool,
+}
+
+fn default_distraction_free_width() -> u16 {
+    80
+}
+
+impl Default for DistractionFreeConfig {
+    fn default() -> Self {
+        Self {
+            width: default_distraction_free_width(),
+            dim_other_paragraphs: false,
+            restore_with_session: false,
+        }
+    }
+}
+
+/// Case matching configuration
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct SearchConfig {
+    /// Search in the buffer case-insensitively unless the query has an
+    /// uppercase letter ("smartcase"). Replaces the case-sensitive toggle,
+    /// which then flips the case matching of the current search only.
+    #[serde(default = "default_false")]
+    pub smart_case: bool,
+
+    /// Smart case for project-wide search and replace
+    #[serde(default = "default_true")]
+    pub project_smart_case: bool,
+
+    /// Rank word completions that match the case of a prefix with an
+    /// uppercase letter first; the others are still offered
+    #[serde(default = "default_true")]
+    pub completion_smart_case: bool,
+
+    /// Replace in the case of the replaced text: replacing `foo` with `bar`
+    /// turns `Foo` into `Bar` and `FOO` into `BAR`
+    #[serde(default = "default_false")]
+    pub preserve_case: bool,
+}
+
+impl Default for SearchConfig {
+    fn default() -> Self {
+        Self {
+            smart_case: false,
+            project_smart_case: true,
+            completion_smart_case: true,
+            preserve_case: false,
+        }
+    }
+}
+
+/// Session snapshot configuration
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct SessionSnapshotConfig {
+    /// Number of timestamped copies of the session kept per project.
+    /// Every session save writes one; 0 turns snapshots off.
+    #[serde(default = "default_session_snapshots")]
+    pub snapshots: usize,
+
+    /// Total size in bytes of the snapshots kept per project.
+    /// The oldest snapshots are removed first; the newest is always kept.
+    #[serde(default = "default_session_snapshots_max_bytes")]
+    pub snapshots_max_bytes: usize,
+}
+
+fn default_session_snapshots() -> usize {
+    10
+}
+
+fn default_session_snapshots_max_bytes() -> usize {
+    16 * 1024 * 1024
+}
+
+impl Default for SessionSnapshotConfig {
+    fn default() -> Self {
+        Self {
+            snapshots: default_session_snapshots(),
+            snapshots_max_bytes: default_session_snapshots_max_bytes(),
+        }
+    }
+}
+
+/// Self-update configuration
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct UpdatesConfig {
+    /// Let "Update Now" download the latest release, verify its checksum and
+    /// replace the running binary. Only for installs from the release
+    /// archives; package manager installs are left to the package manager.
+    /// Nothing is downloaded until "Update Now" is run.
+    /// Default: false
+    #[serde(default = "default_false")]
+    pub self_update: bool,
+}
+
+/// Usage statistics configuration
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct StatsConfig {
+    /// Count commands run, keys pressed, focused time and files opened, per
+    /// day, in the state directory. The counts never leave this machine;
+    /// while off nothing is counted or written.
+    /// Default: false
+    #[serde(default = "default_false")]
+    pub enabled: bool,
+
+    /// Number of days of statistics kept; older days are dropped.
+    #[serde(default = "default_stats_retention_days")]
+    pub retention_days: u32,
+}
+
+fn default_stats_retention_days() -> u32 {
+    90
+}
+
+impl Default for StatsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            retention_days: default_stats_retention_days(),
+        }
+    }
+}
+
+/// Editor log file configuration
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct LogConfig {
+    /// Size in megabytes at which the log file is rotated: renamed to
+    /// `<name>.1`, with older copies moved up to `<name>.2` and so on.
+    /// 0 lets the log grow without limit.
+    #[serde(default = "default_log_max_size_mb")]
+    pub max_size_mb: u64,
+
+    /// Rotated copies of the log kept beside the current file
+    #[serde(default = "default_log_max_files")]
+    pub max_files: usize,
+}
+
+fn default_log_max_size_mb() -> u64 {
+    20
+}
+
+fn default_log_max_files() -> usize {
+    3
+}
+
+impl Default for LogConfig {
+    fn default() -> Self {
+        Self {
+            max_size_mb: default_log_max_size_mb(),
+            max_files: default_log_max_files(),
+        }
+    }
+}
+
+/// Cache and buffer memory configuration
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct PerformanceConfig {
+    /// Megabytes the syntax highlighting caches and the views splits keep of
+    /// buffers they showed before may take together. Past it, the caches
+    /// of background buffers are dropped first.
+    /// 0 picks a budget from the machine's memory (1/32 of it, between
+    /// 64 and 1024 MB).
+    /// Default: 0
+    #[serde(default)]
+    pub cache_budget_mb: u64,
+
+    /// Trim the undo history of buffers that haven't been edited for
+    /// `auto_compact_idle_minutes` down to `auto_compact_undo_depth` steps,
+    /// and compact their text, while the editor is idle.
+    /// Default: false
+    #[serde(default)]
+    pub auto_compact: bool,
+
+    /// Minutes a buffer has to go without edits before `auto_compact`
+    /// touches it.
+    /// Default: 30
+    #[serde(default = "default_auto_compact_idle_minutes")]
+    pub auto_compact_idle_minutes: u64,
+
+    /// Undo steps `auto_compact` leaves in each buffer it compacts.
+    /// Default: 100
+    #[serde(default = "default_auto_compact_undo_depth")]
+    pub auto_compact_undo_depth: usize,
+}
+
+fn default_auto_compact_idle_minutes() -> u64 {
+    30
+}
+
+fn default_auto_compact_undo_depth() -> usize {
+    100
+}
+
+impl Default for PerformanceConfig {
+    fn default() -> Self {
+        Self {
+            cache_budget_mb: 0,
+            auto_compact: false,
+            auto_compact_idle_minutes: default_auto_compact_idle_minutes(),
+            auto_compact_undo_depth: default_auto_compact_undo_depth(),
+        }
+    }
+}
+
+/// User interface configuration
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct UiConfig {
+    /// Terminal cursor shape for each editing state
+    #[serde(default)]
+    pub cursor: CursorShapeConfig,
+
+    /// Which whitespace is drawn with visible symbols. Unusual spaces (such
+    /// as non-breaking spaces) are shown in every mode but "none", and
+    /// whitespace inside a selection is always shown.
+    #[serde(default)]
+    pub render_whitespace: WhitespaceRendering,
+
+    /// Leave the editor background and gutter to the terminal instead of
+    /// painting the theme's, so a translucent or image background shows
+    /// through. Selections, the current line, popups and menus keep their
+    /// own background.
+    #[serde(default)]
+    pub transparent_background: bool,
+
+    /// Columns at which a vertical guide is drawn, e.g. [80, 120]. A guide
+    /// at 80 tints the column right after the 80th. Languages can set their
+    /// own with `languages.<name>.rulers`.
+    #[serde(default)]
+    pub rulers: Vec<u16>,
+
+    /// Tint the part of each line that runs past the last ruler
+    #[serde(default)]
+    pub highlight_overlength: bool,
+
+    /// Draw borders, arrows and markers with ASCII only, for terminals
+    /// that can't display Unicode box drawing. Unset follows the locale:
+    /// on when it isn't UTF-8 (e.g. `LANG=C`).
+    #[serde(default)]
+    pub ascii_only: Option<bool>,
+
+    /// When splits get a winbar: a line at their top with the buffer's
+    /// name and segments set by plugins.
+    /// Options: "never", "multiple_splits", "always"
+    /// Default: multiple_splits
+    #[serde(default)]
+    pub winbar: WinbarVisibility,
+
+    /// Least contrast ratio buffer text must have against its background,
+    /// from 1 to 21 (4.5 is WCAG's level for normal text, 7 the enhanced
+    /// one). Text colors that fall short are lightened or darkened, keeping
+    /// their hue, as they are drawn. Gutters, menus and other UI keep their
+    /// theme colors; "Check Theme Contrast" lists those. Unset leaves every
+    /// color as the theme has it.
+    #[serde(default)]
+    pub minimum_contrast: Option<f64>,
+
+    /// Theme for a light terminal background with `theme = "auto"`, and
+    /// for "Toggle Light/Dark Theme". Unset: "light"
+    #[serde(default)]
+    pub light_theme: Option<ThemeName>,
+
+    /// Theme for a dark terminal background with `theme = "auto"`, and
+    /// for "Toggle Light/Dark Theme". Unset: "dark"
+    #[serde(default)]
+    pub dark_theme: Option<ThemeName>,
+}
+
+/// When splits get a winbar
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum WinbarVisibility {
+    /// Never; data files still get one for `editor.data_path_breadcrumb = "winbar"`
+    Never,
+    /// While more than one split is visible
+    #[default]
+    MultipleSplits,
+    /// Always
+    Always,
+}
+
+impl WinbarVisibility {
+    /// Whether splits get a winbar while `split_count` of them are visible
+    pub fn shows(self, split_count: usize) -> bool {
+        match self {
+            Self::Never => false,
+            Self::MultipleSplits => split_count > 1,
+            Self::Always => true,
+        }
+    }
+}
+
+/// Whitespace drawn with visible symbols
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum WhitespaceRendering {
+    /// Only selected whitespace (and tabs where the language shows them)
+    #[default]
+    None,
+    /// Whitespace at the end of lines
+    Trailing,
+    /// Leading and trailing whitespace, and runs of more than one space
+    Boundary,
+    /// All whitespace
+    All,
+}
+
+/// Terminal cursor shape per editing state.
+///
+/// Unset states fall back to `editor.cursor_style`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct CursorShapeConfig {
+    /// Cursor in read-only buffers and buffers with a plugin mode
+    #[serde(default)]
+    pub normal: Option<CursorStyle>,
+
+    /// Cursor while editing text
+    #[serde(default)]
+    pub insert: Option<CursorStyle>,
+
+    /// Cursor while any cursor has a selection
+    #[serde(default)]
+    pub selection: Option<CursorStyle>,
+
+    /// Cursor for each global editor mode set by plugins (e.g. "vi-normal").
+    /// Takes precedence over the states above while the mode is active.
+    #[serde(default = "default_cursor_modes")]
+    pub modes: HashMap<String, CursorStyle>,
+}
+
+fn default_cursor_modes() -> HashMap<String, CursorStyle> {
+    HashMap::from([
+        ("vi-normal".to_string(), CursorStyle::SteadyBlock),
+        ("vi-insert".to_string(), CursorStyle::BlinkingBar),
+    ])
+}
+
+impl Default for CursorShapeConfig {
+    fn default() -> Self {
+        Self {
+            normal: None,
+            insert: None,
+            selection: None,
+            modes: default_cursor_modes(),
+        }
+    }
+}
+
+/// How open files react to changes on disk
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum AutoRevertMode {
+    /// Reload the buffer automatically
+    #[default]
+    On,
+    /// Keep the buffer as it is
+    Off,
+    /// Ask before reloading
+    Ask,
+}
+
+/// How opening a file that doesn't exist behaves
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum MissingFileMode {
+    /// Open an empty buffer; saving it creates the file
+    #[default]
+    Create,
+    /// Ask before opening the empty buffer
+    Ask,
+}
+
 impl Default for FileExplorerConfig {
     fn default() -> Self {
         Self {
@@ -669,6 +1556,10 @@ impl Default for FileExplorerConfig {
             show_gitignored: false,
             custom_ignore_patterns: Vec::new(),
             width: default_explorer_width(),
+            max_visible_entries: default_explorer_max_visible_entries(),
+            show_entry_counts: false,
+            show_details: false,
+            refresh_debounce_ms: default_explorer_refresh_debounce(),
         }
     }
 }
@@ -747,18 +1638,35 @@ pub struct FormatterConfig {
     pub command: String,
 
     /// Arguments to pass to the formatter
-    /// Use "$FILE" to include the file path
+    /// Use "{file}" (or "$FILE") to include the file path
     #[serde(default)]
     pub args: Vec<String>,
 
     /// Whether to pass buffer content via stdin (default: true)
-    /// Most formatters read from stdin and write to stdout
+    /// Most formatters read from stdin and write to stdout. Set to false for
+    /// formatters that rewrite "{file}" in place: format-on-save then saves
+    /// first and reloads the file after, and Format Buffer formats a copy.
     #[serde(default = "default_true")]
     pub stdin: bool,
 
     /// Timeout in milliseconds (default: 10000)
     #[serde(default = "default_on_save_timeout")]
     pub timeout_ms: u64,
+
+    /// What saving does when format-on-save fails (default: "save")
+    #[serde(default)]
+    pub on_failure: FormatFailure,
+}
+
+/// What saving does when the formatter fails, times out or isn't found
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum FormatFailure {
+    /// Save the unformatted content and show the error
+    #[default]
+    Save,
+    /// Leave the file as it was and show the error
+    Abort,
 }
 
 /// Action to run when a file is saved (for linters, etc.)
@@ -796,6 +1704,101 @@ fn default_on_save_timeout() -> u64 {
     10000
 }
 
+/// What saving does when one of the language's `save_actions` fails
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum SaveActionFailure {
+    /// Show the error, run the remaining steps and save
+    #[default]
+    Continue,
+    /// Show the error, stop and leave the file as it was
+    Abort,
+}
+
+fn default_save_actions_timeout() -> u64 {
+    5000
+}
+
+/// Rule-based auto-indent patterns for a language
+///
+/// Patterns are regular expressions matched against a line's text without its
+/// leading indentation.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct IndentRulesConfig {
+    /// Indent the next line one level deeper when the line before the cursor
+    /// matches one of these (e.g. "\\{[^}]*$" for a line ending with `{`)
+    #[serde(default)]
+    pub increase_indent_after: Vec<String>,
+
+    /// Indent a line one level less when it starts with a match of one of these
+    /// (e.g. "^\\}" or "^else\\b"). Applied to the text moved onto a new line by
+    /// Enter, and when typing makes the line match.
+    #[serde(default)]
+    pub decrease_indent_when: Vec<String>,
+
+    /// Align continuation lines with the first argument after an unclosed
+    /// bracket (e.g. `foo(a,` continues under `a`) instead of indenting one level
+    #[serde(default)]
+    pub align_continuation: bool,
+}
+
+impl IndentRulesConfig {
+    /// Rules for languages with C-style braces
+    fn braces() -> Self {
+        Self {
+            increase_indent_after: vec![
+                r#"\{[^}"'`]*$"#.to_string(),
+                r#"\([^)"'`]*$"#.to_string(),
+                r#"\[[^\]"'`]*$"#.to_string(),
+            ],
+            decrease_indent_when: vec![r"^[}\])]".to_string()],
+            align_continuation: false,
+        }
+    }
+
+    /// Rules for Python-style blocks opened by a colon
+    fn colon_blocks() -> Self {
+        Self {
+            increase_indent_after: vec![
+                r":\s*(#.*)?$".to_string(),
+                r"[(\[{]\s*(#.*)?$".to_string(),
+            ],
+            decrease_indent_when: vec![
+                r"^(elif|else|except|finally)\b.*:\s*(#.*)?$".to_string(),
+                r"^(case|match)\b.*:\s*(#.*)?$".to_string(),
+                r"^[}\])]".to_string(),
+            ],
+            align_continuation: true,
+        }
+    }
+
+    /// Rules for shell scripts
+    fn shell() -> Self {
+        Self {
+            increase_indent_after: vec![
+                r"\b(then|do)\s*(#.*)?$".to_string(),
+                r"^else\s*(#.*)?$".to_string(),
+                r"^case\b.*\bin\s*(#.*)?$".to_string(),
+                r"\{\s*(#.*)?$".to_string(),
+            ],
+            decrease_indent_when: vec![
+                r"^(fi|done|esac|else|elif)\b".to_string(),
+                r"^\}".to_string(),
+            ],
+            align_continuation: false,
+        }
+    }
+
+    /// Rules for YAML, where a key ending with a colon opens a nested block
+    fn yaml() -> Self {
+        Self {
+            increase_indent_after: vec![r":\s*(#.*)?$".to_string()],
+            decrease_indent_when: vec![],
+            align_continuation: false,
+        }
+    }
+}
+
 /// Language-specific configuration
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[schemars(extend("x-display-field" = "/grammar"))]
@@ -804,7 +1807,10 @@ pub struct LanguageConfig {
     #[serde(default)]
     pub extensions: Vec<String>,
 
-    /// Exact filenames for this language (e.g., ["Makefile", "GNUmakefile"])
+    /// Filenames for this language (e.g., ["Makefile", "tsconfig*.json"]).
+    /// `*` and `?` are wildcards, and a pattern with a `/` also names the
+    /// directories the file is in (e.g., ".vscode/*.json").
+    /// These take precedence over `extensions`.
     #[serde(default)]
     pub filenames: Vec<String>,
 
@@ -858,6 +1864,97 @@ pub struct LanguageConfig {
     /// Note: Use `formatter` + `format_on_save` for formatting, not on_save
     #[serde(default)]
     pub on_save: Vec<OnSaveAction>,
+
+    /// Steps run on the buffer before saving, in order and as one undo step:
+    /// "trim_trailing_whitespace", "ensure_final_newline", "format" (the formatter above),
+    /// "lsp:<code action kind>" (e.g. "lsp:source.organizeImports") and "plugin:<plugin>.<step>"
+    /// When set, `format_on_save` is ignored; use "format" instead.
+    #[serde(default)]
+    pub save_actions: Vec<String>,
+
+    /// What saving does when a save action fails (default: "continue")
+    #[serde(default)]
+    pub save_actions_on_failure: SaveActionFailure,
+
+    /// How long an "lsp:" or "plugin:" save action may take, in milliseconds (default: 5000)
+    #[serde(default = "default_save_actions_timeout")]
+    pub save_actions_timeout_ms: u64,
+
+    /// Rule-based auto-indent for Enter and dedent-on-type
+    /// If not specified, indentation is derived from the syntax tree and brackets.
+    #[serde(default)]
+    pub indent_rules: Option<IndentRulesConfig>,
+
+    /// Whether to let the language server reformat as you type (LSP onTypeFormatting)
+    /// When the server supports it, its edits are applied after the indent rules.
+    #[serde(default = "default_false")]
+    pub on_type_formatting: bool,
+
+    /// Ruler columns for this language, replacing `ui.rulers`
+    /// An empty list turns the rulers off for this language.
+    #[serde(default)]
+    pub rulers: Option<Vec<u16>>,
+
+    /// Whether the completion popup opens by itself while typing in this language
+    /// Only applies when `editor.auto_complete` is on.
+    #[serde(default = "default_true")]
+    pub auto_complete: bool,
+
+    /// Text that opens the completion popup right away when typed (e.g. ".", "::", "->"),
+    /// in addition to the trigger characters reported by the language server
+    #[serde(default)]
+    pub completion_trigger_characters: Vec<String>,
+}
+
+impl LanguageConfig {
+    /// Whether `path` matches one of the `filenames` patterns
+    pub fn matches_filename(&self, path: &Path) -> bool {
+        self.filenames
+            .iter()
+            .any(|pattern| filename_pattern_matches(pattern, path))
+    }
+}
+
+/// Match the last components of `path` against a `/`-separated pattern
+fn filename_pattern_matches(pattern: &str, path: &Path) -> bool {
+    let mut components = path
+        .components()
+        .rev()
+        .filter_map(|component| match component {
+            std::path::Component::Normal(name) => Some(name.to_string_lossy()),
+            _ => None,
+        });
+    pattern.rsplit('/').all(|part| {
+        components
+            .next()
+            .is_some_and(|name| wildcard_matches(part, &name))
+    })
+}
+
+/// Match `name` against a pattern where `*` is any run of characters and
+/// `?` any one character
+fn wildcard_matches(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    let (mut p, mut n) = (0, 0);
+    // Where the last `*` was, and how much of the name it has taken
+    let mut star: Option<(usize, usize)> = None;
+    while n < name.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == name[n]) {
+            p += 1;
+            n += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            star = Some((p, n));
+            p += 1;
+        } else if let Some((star_p, star_n)) = star {
+            p = star_p + 1;
+            n = star_n + 1;
+            star = Some((star_p, star_n + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
 }
 
 /// Resolved editor configuration for a specific buffer.
@@ -971,6 +2068,75 @@ impl BufferConfig {
     }
 }
 
+/// Placement of the inline blame annotations
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum InlineBlamePosition {
+    /// A virtual line above the annotated line
+    #[default]
+    LineAbove,
+    /// After the text of the annotated line
+    EndOfLine,
+}
+
+/// Where the key path at the cursor of a data file is shown
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum DataPathBreadcrumb {
+    /// After the cursor position in the status bar
+    #[default]
+    StatusBar,
+    /// A line at the top of each split
+    Winbar,
+    /// Not shown
+    Off,
+}
+
+/// Notation of key paths in data files
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum DataPathStyle {
+    /// `a.b[3]["odd key"]`
+    #[default]
+    Dotted,
+    /// `.a.b[3]."odd key"`, as jq and yq write them
+    Jq,
+}
+
+/// Buffers whose words the completion popup offers
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum WordCompletionScope {
+    /// Words of every open buffer
+    #[default]
+    AllBuffers,
+    /// Words of the buffer being edited
+    CurrentBuffer,
+    /// No word completion
+    Off,
+}
+
+/// Display width of East Asian ambiguous-width characters
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum AmbiguousWidth {
+    /// One cell, as in most western terminals
+    #[default]
+    Narrow,
+    /// Two cells, as in most CJK terminals
+    Wide,
+}
+
+impl AmbiguousWidth {
+    /// Name as reported to plugins and written to config
+    pub fn as_str(self) -> &'static str {
+        match self {
+            AmbiguousWidth::Narrow => "narrow",
+            AmbiguousWidth::Wide => "wide",
+        }
+    }
+}
+
 /// Preference for which syntax highlighting backend to use
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "lowercase")]
@@ -1107,12 +2273,24 @@ impl Default for Config {
             file_explorer: FileExplorerConfig::default(),
             file_browser: FileBrowserConfig::default(),
             terminal: TerminalConfig::default(),
+            mouse: MouseConfig::default(),
             keybindings: vec![], // User customizations only; defaults come from active_keybinding_map
             keybinding_maps: HashMap::new(), // User-defined maps go here
             active_keybinding_map: default_keybinding_map_name(),
             languages: Self::default_languages(),
             lsp: Self::default_lsp_config(),
             warnings: WarningsConfig::default(),
+            todos: TodosConfig::default(),
+            clipboard: ClipboardConfig::default(),
+            files: FilesConfig::default(),
+            distraction_free: DistractionFreeConfig::default(),
+            search: SearchConfig::default(),
+            session: SessionSnapshotConfig::default(),
+            updates: UpdatesConfig::default(),
+            stats: StatsConfig::default(),
+            log: LogConfig::default(),
+            performance: PerformanceConfig::default(),
+            ui: UiConfig::default(),
         }
     }
 }
@@ -1522,6 +2700,13 @@ impl MenuConfig {
                         when: None,
                         checkbox: None,
                     },
+                    MenuItem::Action {
+                        label: t!("menu.selection.select_paragraph").to_string(),
+                        action: "select_paragraph".to_string(),
+                        args: HashMap::new(),
+                        when: None,
+                        checkbox: None,
+                    },
                     MenuItem::Action {
                         label: t!("menu.selection.expand_selection").to_string(),
                         action: "expand_selection".to_string(),
@@ -1529,6 +2714,13 @@ impl MenuConfig {
                         when: None,
                         checkbox: None,
                     },
+                    MenuItem::Action {
+                        label: t!("menu.selection.shrink_selection").to_string(),
+                        action: "shrink_selection".to_string(),
+                        args: HashMap::new(),
+                        when: None,
+                        checkbox: None,
+                    },
                     MenuItem::Separator { separator: true },
                     MenuItem::Action {
                         label: t!("menu.selection.add_cursor_above").to_string(),
@@ -1572,6 +2764,20 @@ impl MenuConfig {
                         when: None,
                         checkbox: None,
                     },
+                    MenuItem::Action {
+                        label: t!("menu.go.goto_byte_offset").to_string(),
+                        action: "goto_byte_offset".to_string(),
+                        args: HashMap::new(),
+                        when: None,
+                        checkbox: None,
+                    },
+                    MenuItem::Action {
+                        label: t!("menu.go.goto_percentage").to_string(),
+                        action: "goto_percentage".to_string(),
+                        args: HashMap::new(),
+                        when: None,
+                        checkbox: None,
+                    },
                     MenuItem::Action {
                         label: t!("menu.go.goto_definition").to_string(),
                         action: "lsp_goto_definition".to_string(),
@@ -1804,6 +3010,7 @@ impl Config {
 
     /// Load configuration from a JSON file
     ///
+    /// The file may have comments and trailing commas (see [`crate::jsonc`]).
     /// This deserializes the user's config file and merges it with defaults.
     /// For HashMap fields like `lsp` and `languages`, entries from the user config
     /// are merged with (and override) the default entries. This allows users to
@@ -1812,8 +3019,8 @@ impl Config {
         let contents = std::fs::read_to_string(path.as_ref())
             .map_err(|e| ConfigError::IoError(e.to_string()))?;
 
-        let mut config: Config =
-            serde_json::from_str(&contents).map_err(|e| ConfigError::ParseError(e.to_string()))?;
+        let mut config: Config = crate::jsonc::from_str(&contents)
+            .map_err(|e| ConfigError::ParseError(e.to_string()))?;
 
         // Merge with defaults for HashMap fields
         config.merge_defaults_for_maps();
@@ -1872,6 +3079,15 @@ impl Config {
     /// Resolve a keymap with inheritance
     /// Returns all bindings from the keymap and its parent chain
     pub fn resolve_keymap(&self, map_name: &str) -> Vec<Keybinding> {
+        self.resolve_keymap_with_sources(map_name)
+            .into_iter()
+            .map(|(_, binding)| binding)
+            .collect()
+    }
+
+    /// Like `resolve_keymap`, pairing each binding with the name of the
+    /// keymap in the inheritance chain that defines it
+    pub fn resolve_keymap_with_sources(&self, map_name: &str) -> Vec<(String, Keybinding)> {
         let mut visited = std::collections::HashSet::new();
         self.resolve_keymap_recursive(map_name, &mut visited)
     }
@@ -1881,7 +3097,7 @@ impl Config {
         &self,
         map_name: &str,
         visited: &mut std::collections::HashSet<String>,
-    ) -> Vec<Keybinding> {
+    ) -> Vec<(String, Keybinding)> {
         // Prevent infinite loops
         if visited.contains(map_name) {
             eprintln!(
@@ -1911,7 +3127,12 @@ impl Config {
         };
 
         // Add this keymap's bindings (they override parent bindings)
-        all_bindings.extend(keymap.bindings);
+        all_bindings.extend(
+            keymap
+                .bindings
+                .into_iter()
+                .map(|binding| (map_name.to_string(), binding)),
+        );
 
         all_bindings
     }
@@ -1937,9 +3158,18 @@ impl Config {
                     args: vec!["--edition".to_string(), "2021".to_string()],
                     stdin: true,
                     timeout_ms: 10000,
+                    on_failure: FormatFailure::Save,
                 }),
                 format_on_save: false,
                 on_save: vec![],
+                indent_rules: Some(IndentRulesConfig::braces()),
+                on_type_formatting: false,
+                rulers: None,
+                auto_complete: true,
+                completion_trigger_characters: vec![],
+                save_actions: Vec::new(),
+                save_actions_on_failure: SaveActionFailure::Continue,
+                save_actions_timeout_ms: 5000,
             },
         );
 
@@ -1958,12 +3188,21 @@ impl Config {
                 tab_size: None,
                 formatter: Some(FormatterConfig {
                     command: "prettier".to_string(),
-                    args: vec!["--stdin-filepath".to_string(), "$FILE".to_string()],
+                    args: vec!["--stdin-filepath".to_string(), "{file}".to_string()],
                     stdin: true,
                     timeout_ms: 10000,
+                    on_failure: FormatFailure::Save,
                 }),
                 format_on_save: false,
                 on_save: vec![],
+                indent_rules: Some(IndentRulesConfig::braces()),
+                on_type_formatting: false,
+                rulers: None,
+                auto_complete: true,
+                completion_trigger_characters: vec![],
+                save_actions: Vec::new(),
+                save_actions_on_failure: SaveActionFailure::Continue,
+                save_actions_timeout_ms: 5000,
             },
         );
 
@@ -1982,12 +3221,21 @@ impl Config {
                 tab_size: None,
                 formatter: Some(FormatterConfig {
                     command: "prettier".to_string(),
-                    args: vec!["--stdin-filepath".to_string(), "$FILE".to_string()],
+                    args: vec!["--stdin-filepath".to_string(), "{file}".to_string()],
                     stdin: true,
                     timeout_ms: 10000,
+                    on_failure: FormatFailure::Save,
                 }),
                 format_on_save: false,
                 on_save: vec![],
+                indent_rules: Some(IndentRulesConfig::braces()),
+                on_type_formatting: false,
+                rulers: None,
+                auto_complete: true,
+                completion_trigger_characters: vec![],
+                save_actions: Vec::new(),
+                save_actions_on_failure: SaveActionFailure::Continue,
+                save_actions_timeout_ms: 5000,
             },
         );
 
@@ -2009,13 +3257,22 @@ impl Config {
                     args: vec![
                         "format".to_string(),
                         "--stdin-filename".to_string(),
-                        "$FILE".to_string(),
+                        "{file}".to_string(),
                     ],
                     stdin: true,
                     timeout_ms: 10000,
+                    on_failure: FormatFailure::Save,
                 }),
                 format_on_save: false,
                 on_save: vec![],
+                indent_rules: Some(IndentRulesConfig::colon_blocks()),
+                on_type_formatting: false,
+                rulers: None,
+                auto_complete: true,
+                completion_trigger_characters: vec![],
+                save_actions: Vec::new(),
+                save_actions_on_failure: SaveActionFailure::Continue,
+                save_actions_timeout_ms: 5000,
             },
         );
 
@@ -2037,9 +3294,18 @@ impl Config {
                     args: vec![],
                     stdin: true,
                     timeout_ms: 10000,
+                    on_failure: FormatFailure::Save,
                 }),
                 format_on_save: false,
                 on_save: vec![],
+                indent_rules: Some(IndentRulesConfig::braces()),
+                on_type_formatting: false,
+                rulers: None,
+                auto_complete: true,
+                completion_trigger_characters: vec![],
+                save_actions: Vec::new(),
+                save_actions_on_failure: SaveActionFailure::Continue,
+                save_actions_timeout_ms: 5000,
             },
         );
 
@@ -2068,9 +3334,18 @@ impl Config {
                     args: vec![],
                     stdin: true,
                     timeout_ms: 10000,
+                    on_failure: FormatFailure::Save,
                 }),
                 format_on_save: false,
                 on_save: vec![],
+                indent_rules: Some(IndentRulesConfig::braces()),
+                on_type_formatting: false,
+                rulers: None,
+                auto_complete: true,
+                completion_trigger_characters: vec![],
+                save_actions: Vec::new(),
+                save_actions_on_failure: SaveActionFailure::Continue,
+                save_actions_timeout_ms: 5000,
             },
         );
 
@@ -2090,6 +3365,14 @@ impl Config {
                 formatter: None,
                 format_on_save: false,
                 on_save: vec![],
+                indent_rules: Some(IndentRulesConfig::braces()),
+                on_type_formatting: false,
+                rulers: None,
+                auto_complete: true,
+                completion_trigger_characters: vec![],
+                save_actions: Vec::new(),
+                save_actions_on_failure: SaveActionFailure::Continue,
+                save_actions_timeout_ms: 5000,
             },
         );
 
@@ -2123,6 +3406,14 @@ impl Config {
                 formatter: None,
                 format_on_save: false,
                 on_save: vec![],
+                indent_rules: Some(IndentRulesConfig::shell()),
+                on_type_formatting: false,
+                rulers: None,
+                auto_complete: true,
+                completion_trigger_characters: vec![],
+                save_actions: Vec::new(),
+                save_actions_on_failure: SaveActionFailure::Continue,
+                save_actions_timeout_ms: 5000,
             },
         );
 
@@ -2146,6 +3437,14 @@ impl Config {
                 formatter: None,
                 format_on_save: false,
                 on_save: vec![],
+                indent_rules: None,
+                on_type_formatting: false,
+                rulers: None,
+                auto_complete: true,
+                completion_trigger_characters: vec![],
+                save_actions: Vec::new(),
+                save_actions_on_failure: SaveActionFailure::Continue,
+                save_actions_timeout_ms: 5000,
             },
         );
 
@@ -2165,13 +3464,21 @@ impl Config {
                 formatter: None,
                 format_on_save: false,
                 on_save: vec![],
+                indent_rules: None,
+                on_type_formatting: false,
+                rulers: None,
+                auto_complete: true,
+                completion_trigger_characters: vec![],
+                save_actions: Vec::new(),
+                save_actions_on_failure: SaveActionFailure::Continue,
+                save_actions_timeout_ms: 5000,
             },
         );
 
         languages.insert(
             "json".to_string(),
             LanguageConfig {
-                extensions: vec!["json".to_string(), "jsonc".to_string()],
+                extensions: vec!["json".to_string()],
                 filenames: vec![],
                 grammar: "json".to_string(),
                 comment_prefix: None,
@@ -2183,12 +3490,61 @@ impl Config {
                 tab_size: None,
                 formatter: Some(FormatterConfig {
                     command: "prettier".to_string(),
-                    args: vec!["--stdin-filepath".to_string(), "$FILE".to_string()],
+                    args: vec!["--stdin-filepath".to_string(), "{file}".to_string()],
                     stdin: true,
                     timeout_ms: 10000,
+                    on_failure: FormatFailure::Save,
                 }),
                 format_on_save: false,
                 on_save: vec![],
+                indent_rules: Some(IndentRulesConfig::braces()),
+                on_type_formatting: false,
+                rulers: None,
+                auto_complete: true,
+                completion_trigger_characters: vec![],
+                save_actions: Vec::new(),
+                save_actions_on_failure: SaveActionFailure::Continue,
+                save_actions_timeout_ms: 5000,
+            },
+        );
+
+        languages.insert(
+            "jsonc".to_string(),
+            LanguageConfig {
+                extensions: vec!["jsonc".to_string()],
+                // JSON files that are read as JSON with comments
+                filenames: vec![
+                    "tsconfig*.json".to_string(),
+                    "jsconfig*.json".to_string(),
+                    ".vscode/*.json".to_string(),
+                    ".fresh/*.json".to_string(),
+                    "fresh/config*.json".to_string(),
+                ],
+                grammar: "json".to_string(),
+                comment_prefix: Some("//".to_string()),
+                auto_indent: true,
+                highlighter: HighlighterPreference::Auto,
+                textmate_grammar: None,
+                show_whitespace_tabs: true,
+                use_tabs: false,
+                tab_size: None,
+                formatter: Some(FormatterConfig {
+                    command: "prettier".to_string(),
+                    args: vec!["--stdin-filepath".to_string(), "{file}".to_string()],
+                    stdin: true,
+                    timeout_ms: 10000,
+                    on_failure: FormatFailure::Save,
+                }),
+                format_on_save: false,
+                on_save: vec![],
+                indent_rules: Some(IndentRulesConfig::braces()),
+                on_type_formatting: false,
+                rulers: None,
+                auto_complete: true,
+                completion_trigger_characters: vec![],
+                save_actions: Vec::new(),
+                save_actions_on_failure: SaveActionFailure::Continue,
+                save_actions_timeout_ms: 5000,
             },
         );
 
@@ -2208,6 +3564,14 @@ impl Config {
                 formatter: None,
                 format_on_save: false,
                 on_save: vec![],
+                indent_rules: None,
+                on_type_formatting: false,
+                rulers: None,
+                auto_complete: true,
+                completion_trigger_characters: vec![],
+                save_actions: Vec::new(),
+                save_actions_on_failure: SaveActionFailure::Continue,
+                save_actions_timeout_ms: 5000,
             },
         );
 
@@ -2226,12 +3590,21 @@ impl Config {
                 tab_size: None,
                 formatter: Some(FormatterConfig {
                     command: "prettier".to_string(),
-                    args: vec!["--stdin-filepath".to_string(), "$FILE".to_string()],
+                    args: vec!["--stdin-filepath".to_string(), "{file}".to_string()],
                     stdin: true,
                     timeout_ms: 10000,
+                    on_failure: FormatFailure::Save,
                 }),
                 format_on_save: false,
                 on_save: vec![],
+                indent_rules: Some(IndentRulesConfig::yaml()),
+                on_type_formatting: false,
+                rulers: None,
+                auto_complete: true,
+                completion_trigger_characters: vec![],
+                save_actions: Vec::new(),
+                save_actions_on_failure: SaveActionFailure::Continue,
+                save_actions_timeout_ms: 5000,
             },
         );
 
@@ -2251,6 +3624,14 @@ impl Config {
                 formatter: None,
                 format_on_save: false,
                 on_save: vec![],
+                indent_rules: None,
+                on_type_formatting: false,
+                rulers: None,
+                auto_complete: true,
+                completion_trigger_characters: vec![],
+                save_actions: Vec::new(),
+                save_actions_on_failure: SaveActionFailure::Continue,
+                save_actions_timeout_ms: 5000,
             },
         );
 
@@ -2273,9 +3654,18 @@ impl Config {
                     args: vec![],
                     stdin: true,
                     timeout_ms: 10000,
+                    on_failure: FormatFailure::Save,
                 }),
                 format_on_save: false,
                 on_save: vec![],
+                indent_rules: Some(IndentRulesConfig::braces()),
+                on_type_formatting: false,
+                rulers: None,
+                auto_complete: true,
+                completion_trigger_characters: vec![],
+                save_actions: Vec::new(),
+                save_actions_on_failure: SaveActionFailure::Continue,
+                save_actions_timeout_ms: 5000,
             },
         );
 
@@ -2283,7 +3673,7 @@ impl Config {
     }
 
     /// Create default LSP configurations
-    fn default_lsp_config() -> HashMap<String, LspServerConfig> {
+    fn default_lsp_config() -> HashMap<String, LspLanguageConfig> {
         let mut lsp = HashMap::new();
 
         // rust-analyzer (installed via rustup or package manager)
@@ -2321,6 +3711,8 @@ impl Config {
                 auto_start: false,
                 process_limits: ProcessLimits::default(),
                 initialization_options: Some(ra_init_options),
+                name: None,
+                primary: false,
             },
         );
 
@@ -2334,6 +3726,8 @@ impl Config {
                 auto_start: false,
                 process_limits: ProcessLimits::default(),
                 initialization_options: None,
+                name: None,
+                primary: false,
             },
         );
 
@@ -2346,6 +3740,8 @@ impl Config {
             auto_start: false,
             process_limits: ProcessLimits::default(),
             initialization_options: None,
+            name: None,
+            primary: false,
         };
         lsp.insert("javascript".to_string(), ts_lsp.clone());
         lsp.insert("typescript".to_string(), ts_lsp);
@@ -2360,6 +3756,8 @@ impl Config {
                 auto_start: false,
                 process_limits: ProcessLimits::default(),
                 initialization_options: None,
+                name: None,
+                primary: false,
             },
         );
 
@@ -2373,6 +3771,8 @@ impl Config {
                 auto_start: false,
                 process_limits: ProcessLimits::default(),
                 initialization_options: None,
+                name: None,
+                primary: false,
             },
         );
 
@@ -2386,6 +3786,8 @@ impl Config {
                 auto_start: false,
                 process_limits: ProcessLimits::default(),
                 initialization_options: None,
+                name: None,
+                primary: false,
             },
         );
         lsp.insert(
@@ -2397,6 +3799,8 @@ impl Config {
                 auto_start: false,
                 process_limits: ProcessLimits::default(),
                 initialization_options: None,
+                name: None,
+                primary: false,
             },
         );
 
@@ -2410,21 +3814,28 @@ impl Config {
                 auto_start: false,
                 process_limits: ProcessLimits::default(),
                 initialization_options: None,
+                name: None,
+                primary: false,
             },
         );
 
         // vscode-json-language-server (installed via npm install -g vscode-langservers-extracted)
-        lsp.insert(
-            "json".to_string(),
-            LspServerConfig {
-                command: "vscode-json-language-server".to_string(),
-                args: vec!["--stdio".to_string()],
-                enabled: true,
-                auto_start: false,
-                process_limits: ProcessLimits::default(),
-                initialization_options: None,
-            },
-        );
+        // serves both JSON and JSON with comments
+        for language in ["json", "jsonc"] {
+            lsp.insert(
+                language.to_string(),
+                LspServerConfig {
+                    command: "vscode-json-language-server".to_string(),
+                    args: vec!["--stdio".to_string()],
+                    enabled: true,
+                    auto_start: false,
+                    process_limits: ProcessLimits::default(),
+                    initialization_options: None,
+                    name: None,
+                    primary: false,
+                },
+            );
+        }
 
         // csharp-language-server (installed via dotnet tool install -g csharp-ls)
         lsp.insert(
@@ -2436,10 +3847,14 @@ impl Config {
                 auto_start: false,
                 process_limits: ProcessLimits::default(),
                 initialization_options: None,
+                name: None,
+                primary: false,
             },
         );
 
-        lsp
+        lsp.into_iter()
+            .map(|(language, server)| (language, server.into()))
+            .collect()
     }
 
     /// Validate the configuration
@@ -2483,6 +3898,8 @@ pub enum ConfigError {
     ParseError(String),
     SerializeError(String),
     ValidationError(String),
+    /// The file changed on disk since it was loaded, so the write was refused
+    Conflict(String),
 }
 
 impl std::fmt::Display for ConfigError {
@@ -2492,6 +3909,7 @@ impl std::fmt::Display for ConfigError {
             ConfigError::ParseError(msg) => write!(f, "Parse error: {msg}"),
             ConfigError::SerializeError(msg) => write!(f, "Serialize error: {msg}"),
             ConfigError::ValidationError(msg) => write!(f, "Validation error: {msg}"),
+            ConfigError::Conflict(msg) => write!(f, "Changed on disk since it was loaded: {msg}"),
         }
     }
 }
@@ -2565,6 +3983,48 @@ mod tests {
         assert_eq!(config.theme, loaded.theme);
     }
 
+    #[test]
+    fn test_config_load_with_comments() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let config_path = temp_dir.path().join("config.json");
+        std::fs::write(
+            &config_path,
+            "{\n  // Two spaces\n  \"editor\": { \"tab_size\": 2, },\n  /* \"theme\": \"x\" */\n}\n",
+        )
+        .unwrap();
+
+        let loaded = Config::load_from_file(&config_path).unwrap();
+        assert_eq!(loaded.editor.tab_size, 2);
+        assert_eq!(loaded.theme, Config::default().theme);
+    }
+
+    #[test]
+    fn test_language_filename_patterns() {
+        let language = LanguageConfig {
+            filenames: vec![
+                "Makefile".to_string(),
+                "tsconfig*.json".to_string(),
+                ".vscode/*.json".to_string(),
+                "?.cfg".to_string(),
+            ],
+            ..Config::default().languages["json"].clone()
+        };
+        let matches = |path: &str| language.matches_filename(Path::new(path));
+
+        assert!(matches("Makefile"));
+        assert!(matches("src/Makefile"));
+        assert!(!matches("Makefile.am"));
+        assert!(matches("tsconfig.json"));
+        assert!(matches("tsconfig.app.json"));
+        assert!(!matches("tsconfig.jsonc"));
+        assert!(matches("/work/.vscode/launch.json"));
+        assert!(matches(".vscode/settings.json"));
+        assert!(!matches("settings.json"));
+        assert!(!matches("/work/.vscode/nested/settings.json"));
+        assert!(matches("a.cfg"));
+        assert!(!matches("ab.cfg"));
+    }
+
     #[test]
     fn test_config_with_custom_keybinding() {
         let json = r#"{
@@ -2611,7 +4071,10 @@ mod tests {
 
         // User's rust override should be present
         assert!(loaded.lsp.contains_key("rust"));
-        assert_eq!(loaded.lsp["rust"].command, "custom-rust-analyzer");
+        assert_eq!(
+            loaded.lsp["rust"].servers()[0].command,
+            "custom-rust-analyzer"
+        );
 
         // Default LSP servers should also be present (merged from defaults)
         assert!(
@@ -2633,6 +4096,31 @@ mod tests {
         assert!(loaded.languages.contains_key("typescript"));
     }
 
+    #[test]
+    fn test_lsp_language_takes_a_list_of_servers() {
+        let json = r#"{
+            "lsp": {
+                "python": [
+                    {"command": "pyright-langserver", "args": ["--stdio"]},
+                    {"command": "/usr/bin/ruff", "args": ["server"], "primary": true}
+                ],
+                "go": {"command": "gopls", "name": "go-main"}
+            }
+        }"#;
+        let config: Config = serde_json::from_str(json).unwrap();
+
+        let python = &config.lsp["python"];
+        let names: Vec<String> = python.servers().iter().map(|s| s.server_name()).collect();
+        assert_eq!(names, ["pyright-langserver", "ruff"]);
+        assert_eq!(python.primary().unwrap().server_name(), "ruff");
+        assert_eq!(python.server("ruff").unwrap().args, ["server"]);
+
+        // A single server reads as before, and is its language's primary
+        let go = &config.lsp["go"];
+        assert_eq!(go.servers().len(), 1);
+        assert_eq!(go.primary().unwrap().server_name(), "go-main");
+    }
+
     #[test]
     fn test_empty_config_gets_all_defaults() {
         let temp_dir = tempfile::tempdir().unwrap();
@@ -2750,9 +4238,18 @@ mod tests {
                     args: vec![],
                     stdin: true,
                     timeout_ms: 10000,
+                    on_failure: FormatFailure::Save,
                 }),
                 format_on_save: true,
                 on_save: vec![],
+                indent_rules: None,
+                on_type_formatting: false,
+                rulers: None,
+                auto_complete: true,
+                completion_trigger_characters: vec![],
+                save_actions: Vec::new(),
+                save_actions_on_failure: SaveActionFailure::Continue,
+                save_actions_timeout_ms: 5000,
             },
         );
 
@@ -0,0 +1,124 @@
+//! JSON printed by the `--dump-*` command line flags
+//!
+//! These let tools read the effective configuration without starting the
+//! editor: nothing here touches the terminal, plugins or any background
+//! service. Loading is strict, so a config file that doesn't load is an
+//! error rather than silently replaced by defaults, which makes the flags
+//! usable as a config check in CI.
+
+use crate::config::{Config, ConfigError};
+use crate::config_io::{error_in_file, DirectoryContext};
+use crate::input::keybindings::{BindingSource, KeyContext, KeybindingResolver, KeymapEntry};
+use serde_json::{json, Map, Value};
+use std::path::Path;
+
+/// Contexts listed by a full keymap dump, in the order they are printed
+pub const KEYMAP_CONTEXTS: [KeyContext; 8] = [
+    KeyContext::Global,
+    KeyContext::Normal,
+    KeyContext::Prompt,
+    KeyContext::Popup,
+    KeyContext::FileExplorer,
+    KeyContext::Menu,
+    KeyContext::Terminal,
+    KeyContext::Settings,
+];
+
+/// Load the config the editor would run with
+///
+/// `config_path` is the `--config` file; without it the working directory's
+/// config and then the user config are used, as on startup.
+pub fn load_config(
+    dir_context: &DirectoryContext,
+    working_dir: &Path,
+    config_path: Option<&Path>,
+) -> Result<Config, ConfigError> {
+    match config_path {
+        Some(path) => Config::load_from_file(path).map_err(|e| error_in_file(e, path)),
+        None => Config::try_load_for_working_dir(dir_context, working_dir),
+    }
+}
+
+/// The fully merged effective config (`--dump-config`)
+pub fn effective_config(config: &Config) -> Result<Value, ConfigError> {
+    serde_json::to_value(config).map_err(|e| ConfigError::SerializeError(e.to_string()))
+}
+
+/// Only the settings of the config file in use, as written
+/// (`--dump-config-user`)
+pub fn user_config(
+    dir_context: &DirectoryContext,
+    working_dir: &Path,
+    config_path: Option<&Path>,
+) -> Result<Value, ConfigError> {
+    match config_path {
+        Some(path) => Config::read_config_file_raw(path),
+        None => Config::try_read_user_config_raw(dir_context, working_dir),
+    }
+}
+
+/// The resolved keymap with the source of every binding (`--dump-keymap`)
+///
+/// An object from context name (as in `when` clauses) to its bindings, for
+/// one context or all of [`KEYMAP_CONTEXTS`]. Mode bindings only exist in a
+/// running editor, so only keymap and user bindings are listed.
+pub fn keymap(config: &Config, context: Option<KeyContext>) -> Value {
+    let resolver = KeybindingResolver::new(config);
+    let contexts = match context {
+        Some(context) => vec![context],
+        None => KEYMAP_CONTEXTS.to_vec(),
+    };
+    let mut map = Map::new();
+    for context in contexts {
+        let entries = resolver.keymap(context).iter().map(entry_json).collect();
+        map.insert(context.to_when_clause().to_string(), Value::Array(entries));
+    }
+    Value::Object(map)
+}
+
+/// The settings JSON schema (`--dump-schema`)
+pub fn schema() -> Value {
+    crate::config::config_schema()
+}
+
+/// One binding; every field is present (null when it doesn't apply) so the
+/// shape is the same for all bindings
+fn entry_json(entry: &KeymapEntry) -> Value {
+    let mut value = json!({
+        "key": entry.key_display(),
+        "action": entry.action,
+        "description": entry.description,
+        "context": entry.context.to_when_clause(),
+        "shadowed": entry
+            .shadowed
+            .iter()
+            .map(|shadowed| {
+                let mut value = json!({
+                    "action": shadowed.action,
+                    "context": shadowed.context.to_when_clause(),
+                });
+                add_source(&mut value, &shadowed.source);
+                value
+            })
+            .collect::<Vec<_>>(),
+    });
+    add_source(&mut value, &entry.source);
+    value
+}
+
+/// Add the "source", "keymap", "mode" and "plugin" fields of a binding
+fn add_source(value: &mut Value, source: &BindingSource) {
+    let (kind, keymap, mode, plugin) = match source {
+        BindingSource::Keymap(name) => ("keymap", Some(name.as_str()), None, None),
+        BindingSource::User => ("user", None, None, None),
+        BindingSource::Mode { mode, plugin } => {
+            ("mode", None, Some(mode.as_str()), plugin.as_deref())
+        }
+    };
+    if let Some(object) = value.as_object_mut() {
+        object.insert("source".to_string(), json!(kind));
+        object.insert("keymap".to_string(), json!(keymap));
+        object.insert("mode".to_string(), json!(mode));
+        object.insert("plugin".to_string(), json!(plugin));
+    }
+}
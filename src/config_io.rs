@@ -7,6 +7,7 @@
 use crate::config::{Config, ConfigError};
 use crate::partial_config::{Merge, PartialConfig, SessionConfig};
 use serde_json::Value;
+use std::borrow::Cow;
 use std::path::{Path, PathBuf};
 
 // ============================================================================
@@ -242,7 +243,7 @@ impl ConfigResolver {
             .map_err(|e| ConfigError::IoError(format!("{}: {}", path.display(), e)))?;
 
         // Parse as raw JSON first
-        let value: Value = serde_json::from_str(&content)
+        let value: Value = crate::jsonc::from_str(&content)
             .map_err(|e| ConfigError::ParseError(format!("{}: {}", path.display(), e)))?;
 
         // Apply migrations
@@ -255,13 +256,70 @@ impl ConfigResolver {
         Ok(Some(partial))
     }
 
+    /// Read a layer file as written, without migrations or defaults.
+    ///
+    /// Returns None if the layer has no file.
+    pub fn read_layer_raw(&self, layer: ConfigLayer) -> Result<Option<Value>, ConfigError> {
+        let Some(path) = self.layer_write_path(layer) else {
+            return Ok(None);
+        };
+        if !path.exists() {
+            return Ok(None);
+        }
+        let content = std::fs::read_to_string(&path)
+            .map_err(|e| ConfigError::IoError(format!("{}: {}", path.display(), e)))?;
+        crate::jsonc::from_str(&content)
+            .map(Some)
+            .map_err(|e| ConfigError::ParseError(format!("{}: {}", path.display(), e)))
+    }
+
+    /// Get the path a layer is written to, or None for the read-only System layer.
+    pub fn layer_write_path(&self, layer: ConfigLayer) -> Option<PathBuf> {
+        match layer {
+            ConfigLayer::User => Some(self.user_config_path()),
+            ConfigLayer::Project => Some(self.project_config_write_path()),
+            ConfigLayer::Session => Some(self.session_config_path()),
+            ConfigLayer::System => None,
+        }
+    }
+
     /// Save a config to a specific layer, writing only the delta from parent layers.
     pub fn save_to_layer(&self, config: &Config, layer: ConfigLayer) -> Result<(), ConfigError> {
-        if layer == ConfigLayer::System {
+        self.save_to_layer_checked(config, layer, WriteCheck::Overwrite)
+            .map(|_| ())
+    }
+
+    /// Save a config to a specific layer, refusing to overwrite changes made on disk
+    /// since the file was loaded (see [`write_config_file`]).
+    ///
+    /// Returns the stamp of the written file.
+    pub fn save_to_layer_checked(
+        &self,
+        config: &Config,
+        layer: ConfigLayer,
+        check: WriteCheck,
+    ) -> Result<ConfigFileStamp, ConfigError> {
+        self.save_to_layer_preserving(config, layer, check, &Value::Null)
+    }
+
+    /// Save a config to a specific layer like [`Self::save_to_layer_checked`],
+    /// also writing `preserved` values the config itself can't hold (settings
+    /// unknown to this version, or stored with another type).
+    ///
+    /// Values in the config's delta take precedence over preserved ones.
+    pub fn save_to_layer_preserving(
+        &self,
+        config: &Config,
+        layer: ConfigLayer,
+        check: WriteCheck,
+        preserved: &Value,
+    ) -> Result<ConfigFileStamp, ConfigError> {
+        // Get path for target layer (use write paths for new configs)
+        let Some(path) = self.layer_write_path(layer) else {
             return Err(ConfigError::ValidationError(
                 "Cannot write to System layer".to_string(),
             ));
-        }
+        };
 
         // Calculate parent config (merge all layers below target)
         let parent_partial = self.resolve_up_to_layer(layer)?;
@@ -277,30 +335,14 @@ impl ConfigResolver {
         // Calculate delta - now both are fully populated, so only actual differences are captured
         let delta = diff_partial_config(&current, &parent);
 
-        // Get path for target layer (use write paths for new configs)
-        let path = match layer {
-            ConfigLayer::User => self.user_config_path(),
-            ConfigLayer::Project => self.project_config_write_path(),
-            ConfigLayer::Session => self.session_config_path(),
-            ConfigLayer::System => unreachable!(),
-        };
-
-        // Ensure parent directory exists
-        if let Some(parent_dir) = path.parent() {
-            std::fs::create_dir_all(parent_dir)
-                .map_err(|e| ConfigError::IoError(format!("{}: {}", parent_dir.display(), e)))?;
-        }
-
         // Write delta to file, stripping null values to keep configs minimal
         let delta_value =
             serde_json::to_value(&delta).map_err(|e| ConfigError::SerializeError(e.to_string()))?;
-        let clean_delta = strip_nulls(delta_value).unwrap_or(Value::Object(Default::default()));
+        let mut clean_delta = strip_nulls(delta_value).unwrap_or(Value::Object(Default::default()));
+        merge_missing(&mut clean_delta, preserved);
         let json = serde_json::to_string_pretty(&clean_delta)
             .map_err(|e| ConfigError::SerializeError(e.to_string()))?;
-        std::fs::write(&path, json)
-            .map_err(|e| ConfigError::IoError(format!("{}: {}", path.display(), e)))?;
-
-        Ok(())
+        write_config_file(&path, &json, check)
     }
 
     /// Save a SessionConfig to the session layer file.
@@ -313,9 +355,10 @@ impl ConfigResolver {
                 .map_err(|e| ConfigError::IoError(format!("{}: {}", parent_dir.display(), e)))?;
         }
 
+        // Session state is volatile, so it is written atomically but not backed up
         let json = serde_json::to_string_pretty(session)
             .map_err(|e| ConfigError::SerializeError(e.to_string()))?;
-        std::fs::write(&path, json)
+        write_file_atomic(&path, json.as_bytes())
             .map_err(|e| ConfigError::IoError(format!("{}: {}", path.display(), e)))?;
 
         tracing::debug!("Saved session config to {}", path.display());
@@ -416,6 +459,22 @@ impl ConfigResolver {
     }
 }
 
+/// Copy the keys of `extra` that `target` doesn't have, recursing into objects
+/// present in both.
+fn merge_missing(target: &mut Value, extra: &Value) {
+    let (Value::Object(target_map), Value::Object(extra_map)) = (target, extra) else {
+        return;
+    };
+    for (key, value) in extra_map {
+        match target_map.get_mut(key) {
+            Some(existing) => merge_missing(existing, value),
+            None => {
+                target_map.insert(key.clone(), value.clone());
+            }
+        }
+    }
+}
+
 /// Recursively collect all non-null leaf paths in a JSON value.
 fn collect_paths<F>(value: &Value, prefix: &str, collector: &mut F)
 where
@@ -453,65 +512,50 @@ fn diff_partial_config(current: &PartialConfig, parent: &PartialConfig) -> Parti
     serde_json::from_value(diff).unwrap_or_default()
 }
 
-impl Config {
-    /// Get the system config file paths (without local/working directory).
-    ///
-    /// On macOS, prioritizes `~/.config/fresh/config.json` if it exists.
-    /// Then checks the standard system config directory.
-    fn system_config_paths() -> Vec<PathBuf> {
-        let mut paths = Vec::with_capacity(2);
-
-        // macOS: Prioritize ~/.config/fresh/config.json
-        #[cfg(target_os = "macos")]
-        if let Some(home) = dirs::home_dir() {
-            let path = home.join(".config").join("fresh").join(Config::FILENAME);
-            if path.exists() {
-                paths.push(path);
-            }
-        }
-
-        // Standard system paths (XDG on Linux, AppSupport on macOS, Roaming on Windows)
-        if let Some(config_dir) = dirs::config_dir() {
-            let path = config_dir.join("fresh").join(Config::FILENAME);
-            if !paths.contains(&path) && path.exists() {
-                paths.push(path);
-            }
-        }
-
-        paths
+/// Prefix a config error's message with the file it came from
+pub(crate) fn error_in_file(error: ConfigError, path: &Path) -> ConfigError {
+    let located = |msg: String| format!("{}: {}", path.display(), msg);
+    match error {
+        ConfigError::IoError(msg) => ConfigError::IoError(located(msg)),
+        ConfigError::ParseError(msg) => ConfigError::ParseError(located(msg)),
+        ConfigError::SerializeError(msg) => ConfigError::SerializeError(located(msg)),
+        ConfigError::ValidationError(msg) => ConfigError::ValidationError(located(msg)),
+        ConfigError::Conflict(msg) => ConfigError::Conflict(located(msg)),
     }
+}
 
+impl Config {
     /// Get all config search paths, checking local (working directory) first.
     ///
     /// Search order:
     /// 1. `{working_dir}/config.json` (project-local config)
-    /// 2. System config paths (see `system_config_paths()`)
+    /// 2. The user config file in `dir_context`'s config directory
     ///
     /// Only returns paths that exist on disk.
-    fn config_search_paths(working_dir: &Path) -> Vec<PathBuf> {
-        let local = Self::local_config_path(working_dir);
-        let mut paths = Vec::with_capacity(3);
-
-        if local.exists() {
-            paths.push(local);
-        }
-
-        paths.extend(Self::system_config_paths());
-        paths
+    fn config_search_paths(dir_context: &DirectoryContext, working_dir: &Path) -> Vec<PathBuf> {
+        [
+            Self::local_config_path(working_dir),
+            dir_context.config_path(),
+        ]
+        .into_iter()
+        .filter(|path| path.exists())
+        .collect()
     }
 
     /// Find the first existing config file, checking local directory first.
     ///
     /// Returns `None` if no config file exists anywhere.
-    pub fn find_config_path(working_dir: &Path) -> Option<PathBuf> {
-        Self::config_search_paths(working_dir).into_iter().next()
+    pub fn find_config_path(dir_context: &DirectoryContext, working_dir: &Path) -> Option<PathBuf> {
+        Self::config_search_paths(dir_context, working_dir)
+            .into_iter()
+            .next()
     }
 
-    /// Load configuration, checking working directory first, then system paths.
+    /// Load configuration, checking working directory first, then the user config.
     ///
     /// Falls back to defaults if no config file is found or all fail to load.
-    pub fn load_for_working_dir(working_dir: &Path) -> Self {
-        for path in Self::config_search_paths(working_dir) {
+    pub fn load_for_working_dir(dir_context: &DirectoryContext, working_dir: &Path) -> Self {
+        for path in Self::config_search_paths(dir_context, working_dir) {
             match Self::load_from_file(&path) {
                 Ok(config) => {
                     tracing::info!("Loaded config from {}", path.display());
@@ -530,6 +574,46 @@ impl Config {
         Self::default()
     }
 
+    /// Load configuration like [`Self::load_for_working_dir`], but fail if
+    /// any config file found can't be loaded instead of skipping it.
+    ///
+    /// The error names the file that failed.
+    pub fn try_load_for_working_dir(
+        dir_context: &DirectoryContext,
+        working_dir: &Path,
+    ) -> Result<Self, ConfigError> {
+        let mut loaded = None;
+        for path in Self::config_search_paths(dir_context, working_dir) {
+            let config = Self::load_from_file(&path).map_err(|e| error_in_file(e, &path))?;
+            loaded.get_or_insert(config);
+        }
+        Ok(loaded.unwrap_or_default())
+    }
+
+    /// Read the config file [`Self::try_load_for_working_dir`] would use, as
+    /// written (only the settings the user set), after checking it loads.
+    ///
+    /// Returns an empty object if there is no config file.
+    pub fn try_read_user_config_raw(
+        dir_context: &DirectoryContext,
+        working_dir: &Path,
+    ) -> Result<serde_json::Value, ConfigError> {
+        Self::try_load_for_working_dir(dir_context, working_dir)?;
+        match Self::find_config_path(dir_context, working_dir) {
+            Some(path) => Self::read_config_file_raw(&path),
+            None => Ok(serde_json::Value::Object(serde_json::Map::new())),
+        }
+    }
+
+    /// Read a config file as written, after checking it loads
+    pub fn read_config_file_raw(path: &Path) -> Result<serde_json::Value, ConfigError> {
+        Self::load_from_file(path).map_err(|e| error_in_file(e, path))?;
+        let contents =
+            std::fs::read_to_string(path).map_err(|e| ConfigError::IoError(e.to_string()))?;
+        crate::jsonc::from_str(&contents)
+            .map_err(|e| error_in_file(ConfigError::ParseError(e.to_string()), path))
+    }
+
     /// Load configuration using the 4-level layer system.
     ///
     /// Merges layers in precedence order: Session > Project > User > System
@@ -554,11 +638,14 @@ impl Config {
     /// with defaults). Useful for plugins that need to distinguish between
     /// user-set values and defaults.
     ///
-    /// Checks working directory first, then system paths.
-    pub fn read_user_config_raw(working_dir: &Path) -> serde_json::Value {
-        for path in Self::config_search_paths(working_dir) {
+    /// Checks working directory first, then the user config.
+    pub fn read_user_config_raw(
+        dir_context: &DirectoryContext,
+        working_dir: &Path,
+    ) -> serde_json::Value {
+        for path in Self::config_search_paths(dir_context, working_dir) {
             if let Ok(contents) = std::fs::read_to_string(&path) {
-                match serde_json::from_str(&contents) {
+                match crate::jsonc::from_str(&contents) {
                     Ok(value) => return value,
                     Err(e) => {
                         tracing::warn!("Failed to parse config from {}: {}", path.display(), e);
@@ -584,12 +671,66 @@ impl Config {
         let contents = serde_json::to_string_pretty(&diff)
             .map_err(|e| ConfigError::SerializeError(e.to_string()))?;
 
-        std::fs::write(path.as_ref(), contents).map_err(|e| ConfigError::IoError(e.to_string()))?;
+        write_config_file(path.as_ref(), &contents, WriteCheck::Overwrite)?;
 
         Ok(())
     }
 }
 
+/// Compute the config values that changed between two serialized configs.
+///
+/// The result can be re-applied on top of another config with [`apply_config_changes`].
+pub fn config_changes(baseline: &Value, current: &Value) -> Value {
+    json_diff(baseline, current)
+}
+
+/// Apply changes computed by [`config_changes`] on top of a serialized config.
+pub fn apply_config_changes(target: &mut Value, changes: &Value) {
+    match (target, changes) {
+        (Value::Object(target_map), Value::Object(changes_map)) => {
+            for (key, change) in changes_map {
+                match target_map.get_mut(key) {
+                    Some(existing) => apply_config_changes(existing, change),
+                    None => {
+                        target_map.insert(key.clone(), change.clone());
+                    }
+                }
+            }
+        }
+        (target, change) => *target = change.clone(),
+    }
+}
+
+/// Return a copy of `config` with the value at a dot-separated key path replaced.
+///
+/// Missing objects along the path are created (e.g. a new `languages` entry); the
+/// result must still deserialize as a valid config.
+pub fn set_config_value(config: &Config, path: &str, value: Value) -> Result<Config, ConfigError> {
+    let invalid = |msg: &str| ConfigError::ValidationError(format!("{}: {}", path, msg));
+
+    let mut root =
+        serde_json::to_value(config).map_err(|e| ConfigError::SerializeError(e.to_string()))?;
+    let mut keys = path.split('.').peekable();
+    let mut node = &mut root;
+    while let Some(key) = keys.next() {
+        if key.is_empty() {
+            return Err(invalid("empty key"));
+        }
+        let Value::Object(map) = node else {
+            return Err(invalid("not an object"));
+        };
+        if keys.peek().is_none() {
+            map.insert(key.to_string(), value);
+            break;
+        }
+        node = map
+            .entry(key.to_string())
+            .or_insert_with(|| Value::Object(Default::default()));
+    }
+
+    serde_json::from_value(root).map_err(|e| invalid(&e.to_string()))
+}
+
 /// Compute the difference between two JSON values.
 /// Returns only the parts of `current` that differ from `defaults`.
 fn json_diff(defaults: &serde_json::Value, current: &serde_json::Value) -> serde_json::Value {
@@ -635,6 +776,243 @@ fn is_empty_diff(value: &serde_json::Value) -> bool {
     }
 }
 
+// ============================================================================
+// Config File Writing
+// ============================================================================
+
+/// Number of previous versions of a config file kept in its backups directory
+pub const CONFIG_BACKUP_COUNT: usize = 5;
+
+/// Fingerprint of a config file's contents, taken when it is loaded or written.
+///
+/// Used for optimistic concurrency: a write is refused if the file on disk no
+/// longer matches the stamp the editor last saw.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConfigFileStamp {
+    len: u64,
+    hash: u64,
+}
+
+impl ConfigFileStamp {
+    /// Stamp for the given file contents
+    pub fn of_contents(contents: &[u8]) -> Self {
+        use std::hash::{Hash, Hasher};
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        contents.hash(&mut hasher);
+        Self {
+            len: contents.len() as u64,
+            hash: hasher.finish(),
+        }
+    }
+
+    /// Stamp of the file at `path`, or None if it doesn't exist
+    pub fn read(path: &Path) -> Result<Option<Self>, ConfigError> {
+        match std::fs::read(path) {
+            Ok(contents) => Ok(Some(Self::of_contents(&contents))),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(ConfigError::IoError(format!("{}: {}", path.display(), e))),
+        }
+    }
+}
+
+/// How [`write_config_file`] treats changes made to the file on disk
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WriteCheck {
+    /// Write regardless of the file's current contents
+    Overwrite,
+    /// Refuse the write unless the file still matches this stamp
+    /// (`None` means the file must not exist)
+    Expect(Option<ConfigFileStamp>),
+}
+
+/// Write a config file atomically, keeping a backup of the previous version.
+///
+/// All config writers go through this function. The previous contents are copied
+/// to `backups/<name>.<timestamp>-<n>.bak` next to the file (keeping the newest
+/// [`CONFIG_BACKUP_COUNT`]), then the new contents are written to a temporary file
+/// in the same directory and renamed over the original, so a crash mid-write
+/// leaves either the old or the new file, never a truncated one.
+///
+/// When the file on disk has comments or trailing commas, `contents` is merged
+/// into it with [`crate::jsonc::update`] so only the changed settings are
+/// rewritten and the comments stay.
+///
+/// Returns [`ConfigError::Conflict`] without touching the file if `check` expects
+/// contents that no longer match what is on disk.
+pub fn write_config_file(
+    path: &Path,
+    contents: &str,
+    check: WriteCheck,
+) -> Result<ConfigFileStamp, ConfigError> {
+    let io_error = |e: std::io::Error| ConfigError::IoError(format!("{}: {}", path.display(), e));
+
+    if let Some(parent_dir) = path.parent() {
+        std::fs::create_dir_all(parent_dir)
+            .map_err(|e| ConfigError::IoError(format!("{}: {}", parent_dir.display(), e)))?;
+    }
+
+    let on_disk = ConfigFileStamp::read(path)?;
+    if let WriteCheck::Expect(expected) = check {
+        if on_disk != expected {
+            return Err(ConfigError::Conflict(path.display().to_string()));
+        }
+    }
+
+    let contents = keep_comments(path, contents);
+    let stamp = ConfigFileStamp::of_contents(contents.as_bytes());
+    if on_disk.is_some() && on_disk != Some(stamp) {
+        // A failed backup shouldn't stop the user from saving
+        if let Err(e) = backup_config_file(path) {
+            tracing::warn!("Failed to back up {}: {}", path.display(), e);
+        }
+    }
+
+    write_file_atomic(path, contents.as_bytes()).map_err(io_error)?;
+    tracing::debug!("Wrote config file {}", path.display());
+    Ok(stamp)
+}
+
+/// `contents` (a JSON document) merged into the current text of `path`, if
+/// that has comments or trailing commas a plain rewrite would drop
+fn keep_comments<'a>(path: &Path, contents: &'a str) -> Cow<'a, str> {
+    let Ok(current) = std::fs::read_to_string(path) else {
+        return Cow::Borrowed(contents);
+    };
+    if !crate::jsonc::has_jsonc_syntax(&current) {
+        return Cow::Borrowed(contents);
+    }
+    let Ok(new_value) = serde_json::from_str::<Value>(contents) else {
+        return Cow::Borrowed(contents);
+    };
+    match crate::jsonc::update(&current, &new_value) {
+        Some(updated) => Cow::Owned(updated),
+        None => {
+            // The backup written below still has them
+            tracing::warn!(
+                "Couldn't keep the comments in {}; rewriting it as plain JSON",
+                path.display()
+            );
+            Cow::Borrowed(contents)
+        }
+    }
+}
+
+/// Directory holding the backups of a config file
+pub fn config_backup_dir(path: &Path) -> PathBuf {
+    path.parent()
+        .unwrap_or_else(|| Path::new("."))
+        .join("backups")
+}
+
+/// Backups of a config file, oldest first
+pub fn config_backups(path: &Path) -> Vec<PathBuf> {
+    let Some(name) = path.file_name().map(|n| n.to_string_lossy().into_owned()) else {
+        return Vec::new();
+    };
+    let prefix = format!("{}.", name);
+    let Ok(entries) = std::fs::read_dir(config_backup_dir(path)) else {
+        return Vec::new();
+    };
+    let mut backups: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|p| {
+            p.file_name()
+                .map(|n| n.to_string_lossy())
+                .is_some_and(|n| n.starts_with(&prefix) && n.ends_with(".bak"))
+        })
+        .collect();
+    // Timestamps are fixed-width, so name order is age order
+    backups.sort();
+    backups
+}
+
+/// Copy the current contents of a config file into its backups directory,
+/// removing the oldest backups beyond [`CONFIG_BACKUP_COUNT`].
+fn backup_config_file(path: &Path) -> std::io::Result<()> {
+    let backup_dir = config_backup_dir(path);
+    std::fs::create_dir_all(&backup_dir)?;
+
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "config.json".to_string());
+    // The counter keeps names unique (and ordered) for writes within the same millisecond
+    let timestamp = chrono::Utc::now().format("%Y%m%dT%H%M%S%.3fZ");
+    let mut counter = 0;
+    let mut backup_path = backup_dir.join(format!("{}.{}-{:03}.bak", name, timestamp, counter));
+    while backup_path.exists() {
+        counter += 1;
+        backup_path = backup_dir.join(format!("{}.{}-{:03}.bak", name, timestamp, counter));
+    }
+    std::fs::copy(path, &backup_path)?;
+
+    let backups = config_backups(path);
+    let excess = backups.len().saturating_sub(CONFIG_BACKUP_COUNT);
+    for old in &backups[..excess] {
+        std::fs::remove_file(old)?;
+    }
+    Ok(())
+}
+
+/// Write `contents` to a temporary file next to `path`, synced to disk.
+///
+/// Returns the temporary file's path; renaming it over `path` completes the write.
+fn write_temp_file(path: &Path, contents: &[u8]) -> std::io::Result<PathBuf> {
+    use std::io::Write;
+
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let temp_path = path.with_file_name(format!(".{}.{}.tmp", name, std::process::id()));
+
+    let result = (|| {
+        let mut file = std::fs::File::create(&temp_path)?;
+        file.write_all(contents)?;
+        // Keep the original file's permissions (config files may be private)
+        if let Ok(metadata) = std::fs::metadata(path) {
+            file.set_permissions(metadata.permissions())?;
+        }
+        file.sync_all()
+    })();
+    if let Err(e) = result {
+        let _ = std::fs::remove_file(&temp_path);
+        return Err(e);
+    }
+    Ok(temp_path)
+}
+
+/// Replace the file at `path` with `contents` via a temporary file and rename.
+///
+/// Symlinked config files (e.g. managed dotfiles) are written through to their target.
+fn write_file_atomic(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+    let target = std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    let temp_path = write_temp_file(&target, contents)?;
+    std::fs::rename(&temp_path, &target).inspect_err(|_| {
+        let _ = std::fs::remove_file(&temp_path);
+    })
+}
+
+/// Marker file next to the executable that turns on portable mode
+pub const PORTABLE_MARKER: &str = "fresh.portable";
+
+/// Directory beside the executable that holds everything in portable mode
+pub const PORTABLE_DATA_DIR: &str = "data";
+
+/// File left in the legacy state directory once the user has answered the
+/// migration prompt
+const MIGRATED_MARKER: &str = "MIGRATED";
+
+/// Entries of the legacy state directory that belong in the state directory
+const STATE_ENTRIES: &[&str] = &[
+    "sessions",
+    "file_states",
+    "recovery",
+    "terminals",
+    "search_history.json",
+    "replace_history.json",
+];
+
 /// Directory paths for editor state and configuration
 ///
 /// This struct holds all directory paths that the editor needs.
@@ -647,14 +1025,29 @@ fn is_empty_diff(value: &serde_json::Value) -> bool {
 /// - No hidden global state dependencies
 #[derive(Debug, Clone)]
 pub struct DirectoryContext {
-    /// Data directory for persistent state (recovery, sessions, history)
-    /// e.g., ~/.local/share/fresh on Linux, ~/Library/Application Support/fresh on macOS
-    pub data_dir: std::path::PathBuf,
-
-    /// Config directory for user configuration
+    /// Config directory for user configuration (config.json, themes, grammars, plugins)
     /// e.g., ~/.config/fresh on Linux, ~/Library/Application Support/fresh on macOS
     pub config_dir: std::path::PathBuf,
 
+    /// State directory for data kept between runs (sessions, recovery, history, logs)
+    /// e.g., ~/.local/state/fresh on Linux, ~/Library/Application Support/fresh on macOS
+    pub state_dir: std::path::PathBuf,
+
+    /// Cache directory for data that can be rebuilt at any time
+    /// e.g., ~/.cache/fresh on Linux, ~/Library/Caches/fresh on macOS
+    pub cache_dir: std::path::PathBuf,
+
+    /// Runtime directory for files tied to the login session (sockets, locks)
+    /// e.g., $XDG_RUNTIME_DIR/fresh on Linux; `None` where there is no such directory
+    pub runtime_dir: Option<std::path::PathBuf>,
+
+    /// Whether all of the above live in a `data/` directory beside the executable
+    pub portable: bool,
+
+    /// Where state was kept before it got its own directory, if that differs
+    /// from `state_dir` (~/.local/share/fresh on Linux)
+    pub legacy_state_dir: Option<std::path::PathBuf>,
+
     /// User's home directory (for file open dialog shortcuts)
     pub home_dir: Option<std::path::PathBuf>,
 
@@ -668,41 +1061,95 @@ pub struct DirectoryContext {
 impl DirectoryContext {
     /// Create a DirectoryContext from the system directories
     /// This should ONLY be called from main()
-    pub fn from_system() -> std::io::Result<Self> {
-        let data_dir = dirs::data_dir()
-            .ok_or_else(|| {
-                std::io::Error::new(
-                    std::io::ErrorKind::NotFound,
-                    "Could not determine data directory",
-                )
-            })?
-            .join("fresh");
+    ///
+    /// `portable` forces portable mode even without a [`PORTABLE_MARKER`].
+    pub fn from_system(portable: bool) -> std::io::Result<Self> {
+        let exe_dir = std::env::current_exe()
+            .ok()
+            .and_then(|exe| exe.parent().map(Path::to_path_buf));
+        let portable_root = exe_dir
+            .as_deref()
+            .and_then(|dir| Self::portable_root(dir, portable));
+        if portable && portable_root.is_none() {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                "Could not determine the executable's directory for portable mode",
+            ));
+        }
+
+        let home_dir = dirs::home_dir();
+        let mut context = Self::resolve(
+            |name| std::env::var_os(name),
+            home_dir.as_deref(),
+            portable_root.as_deref(),
+        )?;
+
+        if !context.portable {
+            context.legacy_state_dir = dirs::data_dir()
+                .map(|dir| dir.join("fresh"))
+                .filter(|dir| *dir != context.state_dir);
+        }
+        context.documents_dir = dirs::document_dir();
+        context.downloads_dir = dirs::download_dir();
+        Ok(context)
+    }
+
+    /// The portable data directory for an executable in `exe_dir`, if portable
+    /// mode is on
+    ///
+    /// Portable mode is on when `forced` (`--portable`) or when a
+    /// [`PORTABLE_MARKER`] file sits next to the executable.
+    pub fn portable_root(exe_dir: &Path, forced: bool) -> Option<PathBuf> {
+        (forced || exe_dir.join(PORTABLE_MARKER).exists()).then(|| exe_dir.join(PORTABLE_DATA_DIR))
+    }
 
-        #[allow(unused_mut)] // mut needed on macOS only
-        let mut config_dir = dirs::config_dir()
-            .ok_or_else(|| {
+    /// Work out the directories from environment variables and the home directory
+    ///
+    /// `env` looks up an environment variable. With a `portable_root`
+    /// everything goes below it and the environment is ignored. Otherwise the
+    /// XDG base directory variables are honoured on Linux and the platform's
+    /// usual locations are used on macOS and Windows.
+    pub fn resolve(
+        env: impl Fn(&str) -> Option<std::ffi::OsString>,
+        home_dir: Option<&Path>,
+        portable_root: Option<&Path>,
+    ) -> std::io::Result<Self> {
+        let home = home_dir.map(Path::to_path_buf);
+
+        if let Some(root) = portable_root {
+            return Ok(Self {
+                config_dir: root.join("config"),
+                state_dir: root.join("state"),
+                cache_dir: root.join("cache"),
+                runtime_dir: None,
+                portable: true,
+                legacy_state_dir: None,
+                home_dir: home,
+                documents_dir: None,
+                downloads_dir: None,
+            });
+        }
+
+        let base = BaseDirs::resolve(&env, home_dir);
+        let require = |dir: Option<PathBuf>, what: &str| {
+            dir.ok_or_else(|| {
                 std::io::Error::new(
                     std::io::ErrorKind::NotFound,
-                    "Could not determine config directory",
+                    format!("Could not determine {} directory", what),
                 )
-            })?
-            .join("fresh");
-
-        // macOS: Prioritize ~/.config/fresh if it exists
-        #[cfg(target_os = "macos")]
-        if let Some(home) = dirs::home_dir() {
-            let xdg_config = home.join(".config").join("fresh");
-            if xdg_config.exists() {
-                config_dir = xdg_config;
-            }
-        }
+            })
+        };
 
         Ok(Self {
-            data_dir,
-            config_dir,
-            home_dir: dirs::home_dir(),
-            documents_dir: dirs::document_dir(),
-            downloads_dir: dirs::download_dir(),
+            config_dir: require(base.config, "config")?,
+            state_dir: require(base.state, "state")?,
+            cache_dir: require(base.cache, "cache")?,
+            runtime_dir: base.runtime,
+            portable: false,
+            legacy_state_dir: None,
+            home_dir: home,
+            documents_dir: None,
+            downloads_dir: None,
         })
     }
 
@@ -710,8 +1157,12 @@ impl DirectoryContext {
     /// All paths point to subdirectories within the provided temp_dir
     pub fn for_testing(temp_dir: &std::path::Path) -> Self {
         Self {
-            data_dir: temp_dir.join("data"),
             config_dir: temp_dir.join("config"),
+            state_dir: temp_dir.join("state"),
+            cache_dir: temp_dir.join("cache"),
+            runtime_dir: Some(temp_dir.join("runtime")),
+            portable: false,
+            legacy_state_dir: None,
             home_dir: Some(temp_dir.join("home")),
             documents_dir: Some(temp_dir.join("documents")),
             downloads_dir: Some(temp_dir.join("downloads")),
@@ -720,27 +1171,63 @@ impl DirectoryContext {
 
     /// Get the recovery directory path
     pub fn recovery_dir(&self) -> std::path::PathBuf {
-        self.data_dir.join("recovery")
+        self.state_dir.join("recovery")
     }
 
     /// Get the sessions directory path
     pub fn sessions_dir(&self) -> std::path::PathBuf {
-        self.data_dir.join("sessions")
+        self.state_dir.join("sessions")
+    }
+
+    /// Get the directory of per-file states (cursor and scroll positions)
+    pub fn file_states_dir(&self) -> std::path::PathBuf {
+        self.state_dir.join("file_states")
+    }
+
+    /// Get the directory of local copies of remote files that couldn't be saved
+    pub fn remote_copies_dir(&self) -> std::path::PathBuf {
+        self.state_dir.join("remote_copies")
+    }
+
+    /// Get the directory of the sockets of shared ssh connections, if there
+    /// is a runtime directory to keep them in
+    pub fn ssh_control_dir(&self) -> Option<std::path::PathBuf> {
+        self.runtime_dir.as_ref().map(|dir| dir.join("ssh"))
+    }
+
+    /// Get the path of the per-project decisions on project-supplied commands
+    pub fn command_trust_path(&self) -> std::path::PathBuf {
+        self.state_dir.join("command_trust.json")
+    }
+
+    /// Get the path of the recently used project directories
+    pub fn recent_projects_path(&self) -> std::path::PathBuf {
+        self.state_dir.join("recent_projects.json")
+    }
+
+    /// Get the path of the local usage statistics
+    pub fn usage_stats_path(&self) -> std::path::PathBuf {
+        self.state_dir.join("usage_stats.json")
+    }
+
+    /// Get the directory of files replaced by settings imports
+    pub fn settings_backups_dir(&self) -> std::path::PathBuf {
+        self.state_dir.join("settings_backups")
     }
 
     /// Get the search history file path
     pub fn search_history_path(&self) -> std::path::PathBuf {
-        self.data_dir.join("search_history.json")
+        self.state_dir.join("search_history.json")
     }
 
     /// Get the replace history file path
     pub fn replace_history_path(&self) -> std::path::PathBuf {
-        self.data_dir.join("replace_history.json")
+        self.state_dir.join("replace_history.json")
     }
 
     /// Get the terminals root directory
     pub fn terminals_dir(&self) -> std::path::PathBuf {
-        self.data_dir.join("terminals")
+        self.state_dir.join("terminals")
     }
 
     /// Get the terminal directory for a specific working directory
@@ -749,6 +1236,11 @@ impl DirectoryContext {
         self.terminals_dir().join(encoded)
     }
 
+    /// Get the log directory path
+    pub fn logs_dir(&self) -> std::path::PathBuf {
+        self.state_dir.join("logs")
+    }
+
     /// Get the config file path
     pub fn config_path(&self) -> std::path::PathBuf {
         self.config_dir.join(Config::FILENAME)
@@ -768,6 +1260,183 @@ impl DirectoryContext {
     pub fn plugins_dir(&self) -> std::path::PathBuf {
         self.config_dir.join("plugins")
     }
+
+    /// Get the directory of cached transpiled plugins
+    pub fn transpile_cache_dir(&self) -> std::path::PathBuf {
+        self.cache_dir.join("transpiled")
+    }
+
+    /// State left in the legacy state directory that the user hasn't been
+    /// asked about yet
+    pub fn pending_state_migration(&self) -> Option<StateMigration> {
+        let from = self.legacy_state_dir.as_ref()?;
+        if from.join(MIGRATED_MARKER).exists() {
+            return None;
+        }
+        let entries: Vec<String> = STATE_ENTRIES
+            .iter()
+            .filter(|name| from.join(name).exists())
+            .map(|name| name.to_string())
+            .collect();
+        (!entries.is_empty()).then(|| StateMigration {
+            from: from.clone(),
+            to: self.state_dir.clone(),
+            entries,
+        })
+    }
+}
+
+/// Per-platform base directories, already joined with `fresh`
+struct BaseDirs {
+    config: Option<PathBuf>,
+    state: Option<PathBuf>,
+    cache: Option<PathBuf>,
+    runtime: Option<PathBuf>,
+}
+
+impl BaseDirs {
+    #[cfg(not(any(target_os = "macos", windows)))]
+    fn resolve(env: &impl Fn(&str) -> Option<std::ffi::OsString>, home: Option<&Path>) -> Self {
+        let xdg = |var: &str, fallback: &str| {
+            xdg_dir(env, var)
+                .or_else(|| home.map(|home| home.join(fallback)))
+                .map(|dir| dir.join("fresh"))
+        };
+        Self {
+            config: xdg("XDG_CONFIG_HOME", ".config"),
+            state: xdg("XDG_STATE_HOME", ".local/state"),
+            cache: xdg("XDG_CACHE_HOME", ".cache"),
+            runtime: xdg_dir(env, "XDG_RUNTIME_DIR").map(|dir| dir.join("fresh")),
+        }
+    }
+
+    #[cfg(target_os = "macos")]
+    fn resolve(env: &impl Fn(&str) -> Option<std::ffi::OsString>, home: Option<&Path>) -> Self {
+        let app_support = home.map(|home| home.join("Library/Application Support/fresh"));
+        // Prefer an XDG-style config directory if the user has created one
+        let xdg_config = xdg_dir(env, "XDG_CONFIG_HOME")
+            .or_else(|| home.map(|home| home.join(".config")))
+            .map(|dir| dir.join("fresh"))
+            .filter(|dir| dir.exists());
+        Self {
+            config: xdg_config.or_else(|| app_support.clone()),
+            state: app_support,
+            cache: home.map(|home| home.join("Library/Caches/fresh")),
+            runtime: xdg_dir(env, "XDG_RUNTIME_DIR").map(|dir| dir.join("fresh")),
+        }
+    }
+
+    #[cfg(windows)]
+    fn resolve(env: &impl Fn(&str) -> Option<std::ffi::OsString>, home: Option<&Path>) -> Self {
+        let known = |var: &str, fallback: &str| {
+            env(var)
+                .map(PathBuf::from)
+                .filter(|dir| dir.is_absolute())
+                .or_else(|| home.map(|home| home.join("AppData").join(fallback)))
+                .map(|dir| dir.join("fresh"))
+        };
+        let local = known("LOCALAPPDATA", "Local");
+        Self {
+            config: known("APPDATA", "Roaming"),
+            cache: local.as_ref().map(|dir| dir.join("cache")),
+            state: local,
+            runtime: None,
+        }
+    }
+}
+
+/// An XDG base directory from `var`
+///
+/// Unset, empty and relative values are ignored, as the XDG spec requires.
+#[cfg_attr(windows, allow(dead_code))]
+fn xdg_dir(env: &impl Fn(&str) -> Option<std::ffi::OsString>, var: &str) -> Option<PathBuf> {
+    env(var).map(PathBuf::from).filter(|dir| dir.is_absolute())
+}
+
+/// State found in the legacy state directory, waiting to be moved
+#[derive(Debug, Clone, PartialEq)]
+pub struct StateMigration {
+    /// Legacy state directory
+    pub from: PathBuf,
+    /// Current state directory
+    pub to: PathBuf,
+    /// Names of the entries in `from` to move
+    pub entries: Vec<String>,
+}
+
+/// Outcome of [`StateMigration::run`]
+#[derive(Debug, Default)]
+pub struct MigrationReport {
+    /// Number of files and directories moved
+    pub moved: usize,
+    /// Files left behind because the state directory already has them
+    pub skipped: Vec<PathBuf>,
+}
+
+impl StateMigration {
+    /// Move the entries into the state directory and don't ask again
+    ///
+    /// Directories are merged. A file that exists in both places is never
+    /// overwritten or copied: the one in the state directory wins and the old
+    /// one stays where it is and is reported in [`MigrationReport::skipped`].
+    pub fn run(&self) -> std::io::Result<MigrationReport> {
+        let mut report = MigrationReport::default();
+        std::fs::create_dir_all(&self.to)?;
+        for name in &self.entries {
+            move_merging(&self.from.join(name), &self.to.join(name), &mut report)?;
+        }
+        self.dismiss()?;
+        Ok(report)
+    }
+
+    /// Leave the legacy state where it is and don't ask again
+    pub fn dismiss(&self) -> std::io::Result<()> {
+        std::fs::write(
+            self.from.join(MIGRATED_MARKER),
+            format!("Fresh keeps its state in {}\n", self.to.display()),
+        )
+    }
+}
+
+fn move_merging(from: &Path, to: &Path, report: &mut MigrationReport) -> std::io::Result<()> {
+    if !to.exists() {
+        if std::fs::rename(from, to).is_err() {
+            // Different file system: copy, then remove the original
+            copy_recursive(from, to)?;
+            if from.is_dir() {
+                std::fs::remove_dir_all(from)?;
+            } else {
+                std::fs::remove_file(from)?;
+            }
+        }
+        report.moved += 1;
+        return Ok(());
+    }
+
+    if from.is_dir() && to.is_dir() {
+        for entry in std::fs::read_dir(from)? {
+            let entry = entry?;
+            move_merging(&entry.path(), &to.join(entry.file_name()), report)?;
+        }
+        // Only succeeds once everything inside has been moved
+        let _ = std::fs::remove_dir(from);
+    } else {
+        report.skipped.push(from.to_path_buf());
+    }
+    Ok(())
+}
+
+fn copy_recursive(from: &Path, to: &Path) -> std::io::Result<()> {
+    if from.is_dir() {
+        std::fs::create_dir_all(to)?;
+        for entry in std::fs::read_dir(from)? {
+            let entry = entry?;
+            copy_recursive(&entry.path(), &to.join(entry.file_name()))?;
+        }
+        Ok(())
+    } else {
+        std::fs::copy(from, to).map(|_| ())
+    }
 }
 
 #[cfg(test)]
@@ -1136,6 +1805,37 @@ mod tests {
         drop(temp);
     }
 
+    #[test]
+    fn save_to_layer_keeps_comments() {
+        let (_temp, resolver) = create_test_resolver();
+        let user_config_path = resolver.user_config_path();
+        std::fs::create_dir_all(user_config_path.parent().unwrap()).unwrap();
+        let original = r#"{
+  // Narrow indentation for this laptop
+  "editor": {
+    "tab_size": 2, // not 4
+    "line_numbers": false,
+  },
+}
+"#;
+        std::fs::write(&user_config_path, original).unwrap();
+
+        let mut config = resolver.resolve().unwrap();
+        assert_eq!(config.editor.tab_size, 2);
+        config.editor.tab_size = 8;
+        resolver.save_to_layer(&config, ConfigLayer::User).unwrap();
+
+        // Loading migrated the file, so it is saved with its version
+        let content = std::fs::read_to_string(&user_config_path).unwrap();
+        assert_eq!(
+            content,
+            original
+                .replace("\"tab_size\": 2", "\"tab_size\": 8")
+                .replace("  },\n}", "  },\n  \"version\": 1,\n}")
+        );
+        assert_eq!(resolver.resolve().unwrap().editor.tab_size, 8);
+    }
+
     #[test]
     fn save_to_layer_removes_inherited_values() {
         let (temp, resolver) = create_test_resolver();
@@ -1174,4 +1874,337 @@ mod tests {
 
         drop(temp);
     }
+
+    #[test]
+    fn save_to_layer_keeps_preserved_values() {
+        let (_temp, resolver) = create_test_resolver();
+        let mut config = Config::default();
+        config.editor.tab_size = 2;
+        config.editor.line_wrap = false;
+
+        let preserved = serde_json::json!({
+            "legacy_option": { "enabled": true },
+            "editor": { "tab_size": 8, "old_key": "x" }
+        });
+        resolver
+            .save_to_layer_preserving(
+                &config,
+                ConfigLayer::User,
+                WriteCheck::Overwrite,
+                &preserved,
+            )
+            .unwrap();
+
+        let saved = resolver.read_layer_raw(ConfigLayer::User).unwrap().unwrap();
+        assert_eq!(
+            saved,
+            serde_json::json!({
+                "legacy_option": { "enabled": true },
+                "editor": { "tab_size": 2, "line_wrap": false, "old_key": "x" }
+            })
+        );
+    }
+
+    #[test]
+    fn write_config_file_rotates_backups() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("config.json");
+
+        // First write creates the file; there is nothing to back up yet
+        write_config_file(&path, "{\"v\": 0}", WriteCheck::Overwrite).unwrap();
+        assert!(config_backups(&path).is_empty());
+
+        for i in 1..=CONFIG_BACKUP_COUNT + 2 {
+            write_config_file(&path, &format!("{{\"v\": {}}}", i), WriteCheck::Overwrite).unwrap();
+        }
+
+        let backups = config_backups(&path);
+        assert_eq!(backups.len(), CONFIG_BACKUP_COUNT);
+        // The oldest versions were rotated out; the newest backup is the previous version
+        let oldest = std::fs::read_to_string(&backups[0]).unwrap();
+        assert_eq!(oldest, "{\"v\": 2}");
+        let newest = std::fs::read_to_string(backups.last().unwrap()).unwrap();
+        assert_eq!(newest, format!("{{\"v\": {}}}", CONFIG_BACKUP_COUNT + 1));
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            format!("{{\"v\": {}}}", CONFIG_BACKUP_COUNT + 2)
+        );
+
+        // Rewriting identical contents doesn't push a duplicate backup
+        let before = config_backups(&path);
+        write_config_file(
+            &path,
+            &format!("{{\"v\": {}}}", CONFIG_BACKUP_COUNT + 2),
+            WriteCheck::Overwrite,
+        )
+        .unwrap();
+        assert_eq!(config_backups(&path), before);
+    }
+
+    #[test]
+    fn write_config_file_refuses_when_changed_on_disk() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("config.json");
+
+        let stamp =
+            write_config_file(&path, r#"{"theme": "dark"}"#, WriteCheck::Overwrite).unwrap();
+        assert_eq!(ConfigFileStamp::read(&path).unwrap(), Some(stamp));
+
+        // Unchanged since the stamp was taken: the write goes through
+        let stamp = write_config_file(
+            &path,
+            r#"{"theme": "light"}"#,
+            WriteCheck::Expect(Some(stamp)),
+        )
+        .unwrap();
+
+        // Someone else edits the file
+        std::fs::write(&path, r#"{"theme": "nord"}"#).unwrap();
+
+        let result = write_config_file(
+            &path,
+            r#"{"theme": "dracula"}"#,
+            WriteCheck::Expect(Some(stamp)),
+        );
+        assert!(matches!(result, Err(ConfigError::Conflict(_))));
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            r#"{"theme": "nord"}"#
+        );
+
+        // A file that was absent at load time must still be absent
+        let new_path = temp.path().join("other.json");
+        std::fs::write(&new_path, "{}").unwrap();
+        let result = write_config_file(&new_path, "{}", WriteCheck::Expect(None));
+        assert!(matches!(result, Err(ConfigError::Conflict(_))));
+
+        // Overwrite ignores the conflict
+        write_config_file(&path, r#"{"theme": "dracula"}"#, WriteCheck::Overwrite).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            r#"{"theme": "dracula"}"#
+        );
+    }
+
+    #[test]
+    fn interrupted_write_leaves_original_intact() {
+        let (_temp, resolver) = create_test_resolver();
+        let path = resolver.user_config_path();
+        write_config_file(
+            &path,
+            r#"{"editor": {"tab_size": 2}}"#,
+            WriteCheck::Overwrite,
+        )
+        .unwrap();
+
+        // Simulate a crash after the temp file is written but before the rename
+        let temp_path = write_temp_file(&path, br#"{"editor": {"tab_si"#).unwrap();
+        assert!(temp_path.exists());
+
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            r#"{"editor": {"tab_size": 2}}"#
+        );
+        assert_eq!(resolver.resolve().unwrap().editor.tab_size, 2);
+
+        // The next write replaces the leftover temp file and succeeds
+        let mut config = resolver.resolve().unwrap();
+        config.editor.tab_size = 6;
+        resolver.save_to_layer(&config, ConfigLayer::User).unwrap();
+        assert!(!temp_path.exists());
+        assert_eq!(resolver.resolve().unwrap().editor.tab_size, 6);
+    }
+
+    #[test]
+    fn set_config_value_by_key_path() {
+        let config = Config::default();
+
+        let updated = set_config_value(&config, "editor.tab_size", serde_json::json!(2)).unwrap();
+        assert_eq!(updated.editor.tab_size, 2);
+
+        assert!(set_config_value(&config, "editor.tab_size", serde_json::json!("wide")).is_err());
+        assert!(set_config_value(&config, "editor.tab_size.x", serde_json::json!(1)).is_err());
+        assert!(set_config_value(&config, "", serde_json::json!(1)).is_err());
+    }
+
+    #[test]
+    fn config_changes_reapply_on_top_of_other_edits() {
+        let baseline =
+            serde_json::json!({"theme": "dark", "editor": {"tab_size": 4, "line_numbers": true}});
+        let mine =
+            serde_json::json!({"theme": "dark", "editor": {"tab_size": 2, "line_numbers": true}});
+        let mut theirs =
+            serde_json::json!({"theme": "nord", "editor": {"tab_size": 4, "line_numbers": false}});
+
+        let changes = config_changes(&baseline, &mine);
+        assert_eq!(changes, serde_json::json!({"editor": {"tab_size": 2}}));
+
+        apply_config_changes(&mut theirs, &changes);
+        assert_eq!(
+            theirs,
+            serde_json::json!({"theme": "nord", "editor": {"tab_size": 2, "line_numbers": false}})
+        );
+    }
+
+    fn env_from(vars: &[(&str, &str)]) -> impl Fn(&str) -> Option<std::ffi::OsString> {
+        let vars: Vec<(String, String)> = vars
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        move |name| vars.iter().find(|(k, _)| k == name).map(|(_, v)| v.into())
+    }
+
+    #[test]
+    #[cfg(not(any(target_os = "macos", windows)))]
+    fn xdg_defaults_without_env() {
+        let home = Path::new("/home/u");
+        let dirs = DirectoryContext::resolve(env_from(&[]), Some(home), None).unwrap();
+        assert_eq!(dirs.config_dir, home.join(".config/fresh"));
+        assert_eq!(dirs.state_dir, home.join(".local/state/fresh"));
+        assert_eq!(dirs.cache_dir, home.join(".cache/fresh"));
+        assert_eq!(dirs.runtime_dir, None);
+        assert!(!dirs.portable);
+    }
+
+    #[test]
+    #[cfg(not(any(target_os = "macos", windows)))]
+    fn xdg_env_vars_are_respected() {
+        let env = env_from(&[
+            ("XDG_CONFIG_HOME", "/cfg"),
+            ("XDG_STATE_HOME", "/state"),
+            ("XDG_CACHE_HOME", "/cache"),
+            ("XDG_RUNTIME_DIR", "/run/user/1000"),
+        ]);
+        let dirs = DirectoryContext::resolve(env, Some(Path::new("/home/u")), None).unwrap();
+        assert_eq!(dirs.config_dir, PathBuf::from("/cfg/fresh"));
+        assert_eq!(dirs.state_dir, PathBuf::from("/state/fresh"));
+        assert_eq!(dirs.cache_dir, PathBuf::from("/cache/fresh"));
+        assert_eq!(
+            dirs.runtime_dir,
+            Some(PathBuf::from("/run/user/1000/fresh"))
+        );
+        assert_eq!(dirs.sessions_dir(), PathBuf::from("/state/fresh/sessions"));
+        assert_eq!(dirs.logs_dir(), PathBuf::from("/state/fresh/logs"));
+    }
+
+    #[test]
+    #[cfg(not(any(target_os = "macos", windows)))]
+    fn xdg_empty_and_relative_env_vars_are_ignored() {
+        let env = env_from(&[
+            ("XDG_CONFIG_HOME", ""),
+            ("XDG_STATE_HOME", "relative/state"),
+        ]);
+        let home = Path::new("/home/u");
+        let dirs = DirectoryContext::resolve(env, Some(home), None).unwrap();
+        assert_eq!(dirs.config_dir, home.join(".config/fresh"));
+        assert_eq!(dirs.state_dir, home.join(".local/state/fresh"));
+    }
+
+    #[test]
+    #[cfg(not(any(target_os = "macos", windows)))]
+    fn xdg_without_home_needs_env_vars() {
+        assert!(DirectoryContext::resolve(env_from(&[]), None, None).is_err());
+
+        let env = env_from(&[
+            ("XDG_CONFIG_HOME", "/cfg"),
+            ("XDG_STATE_HOME", "/state"),
+            ("XDG_CACHE_HOME", "/cache"),
+        ]);
+        let dirs = DirectoryContext::resolve(env, None, None).unwrap();
+        assert_eq!(dirs.state_dir, PathBuf::from("/state/fresh"));
+    }
+
+    #[test]
+    fn portable_root_overrides_environment() {
+        let env = env_from(&[("XDG_CONFIG_HOME", "/cfg"), ("APPDATA", "/appdata")]);
+        let root = Path::new("/usb/fresh/data");
+        let dirs = DirectoryContext::resolve(env, Some(Path::new("/home/u")), Some(root)).unwrap();
+        assert!(dirs.portable);
+        assert_eq!(dirs.config_dir, root.join("config"));
+        assert_eq!(dirs.state_dir, root.join("state"));
+        assert_eq!(dirs.cache_dir, root.join("cache"));
+        assert_eq!(dirs.runtime_dir, None);
+        assert_eq!(dirs.legacy_state_dir, None);
+    }
+
+    #[test]
+    fn portable_root_from_flag_or_marker() {
+        let temp = TempDir::new().unwrap();
+        let exe_dir = temp.path();
+        assert_eq!(DirectoryContext::portable_root(exe_dir, false), None);
+        assert_eq!(
+            DirectoryContext::portable_root(exe_dir, true),
+            Some(exe_dir.join(PORTABLE_DATA_DIR))
+        );
+
+        std::fs::write(exe_dir.join(PORTABLE_MARKER), "").unwrap();
+        assert_eq!(
+            DirectoryContext::portable_root(exe_dir, false),
+            Some(exe_dir.join(PORTABLE_DATA_DIR))
+        );
+    }
+
+    fn context_with_legacy(temp: &TempDir) -> DirectoryContext {
+        let mut dir_context = DirectoryContext::for_testing(temp.path());
+        dir_context.legacy_state_dir = Some(temp.path().join("legacy"));
+        dir_context
+    }
+
+    #[test]
+    fn state_migration_moves_legacy_state_once() {
+        let temp = TempDir::new().unwrap();
+        let dir_context = context_with_legacy(&temp);
+        assert_eq!(dir_context.pending_state_migration(), None);
+
+        let legacy = temp.path().join("legacy");
+        std::fs::create_dir_all(legacy.join("sessions")).unwrap();
+        std::fs::write(legacy.join("sessions/a.json"), "old a").unwrap();
+        std::fs::write(legacy.join("sessions/b.json"), "old b").unwrap();
+        std::fs::write(legacy.join("search_history.json"), "[]").unwrap();
+        std::fs::write(legacy.join("unrelated.txt"), "").unwrap();
+
+        // A session saved since the change is kept, not overwritten
+        std::fs::create_dir_all(dir_context.sessions_dir()).unwrap();
+        std::fs::write(dir_context.sessions_dir().join("b.json"), "new b").unwrap();
+
+        let migration = dir_context.pending_state_migration().unwrap();
+        assert_eq!(migration.entries, vec!["sessions", "search_history.json"]);
+
+        let report = migration.run().unwrap();
+        assert_eq!(report.moved, 2);
+        assert_eq!(report.skipped, vec![legacy.join("sessions/b.json")]);
+
+        let sessions = dir_context.sessions_dir();
+        assert_eq!(
+            std::fs::read_to_string(sessions.join("a.json")).unwrap(),
+            "old a"
+        );
+        assert_eq!(
+            std::fs::read_to_string(sessions.join("b.json")).unwrap(),
+            "new b"
+        );
+        assert!(dir_context.search_history_path().exists());
+        assert!(!legacy.join("sessions/a.json").exists());
+        assert!(legacy.join("sessions/b.json").exists());
+        assert!(legacy.join("unrelated.txt").exists());
+
+        assert_eq!(dir_context.pending_state_migration(), None);
+    }
+
+    #[test]
+    fn dismissed_state_migration_is_not_offered_again() {
+        let temp = TempDir::new().unwrap();
+        let dir_context = context_with_legacy(&temp);
+        let legacy = temp.path().join("legacy");
+        std::fs::create_dir_all(legacy.join("recovery")).unwrap();
+
+        dir_context
+            .pending_state_migration()
+            .unwrap()
+            .dismiss()
+            .unwrap();
+        assert_eq!(dir_context.pending_state_migration(), None);
+        assert!(legacy.join("recovery").exists());
+        assert!(!dir_context.recovery_dir().exists());
+    }
 }
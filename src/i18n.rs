@@ -201,6 +201,13 @@ pub fn switched_to_project_message(path: &str) -> String {
     rust_i18n::t!("file.switched_to_project", path = path).to_string()
 }
 
+/// Get the translated message for "error opening file".
+///
+/// For use by the binary crate (main.rs), like [`switched_to_project_message`].
+pub fn error_opening_file_message(error: &str) -> String {
+    rust_i18n::t!("file.error_opening", error = error).to_string()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
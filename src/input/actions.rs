@@ -1,10 +1,11 @@
 //! Action to event conversion - translates high-level actions into buffer events
 
 use crate::input::keybindings::Action;
+use crate::input::selection_expansion;
 use crate::model::buffer::{Buffer, LineEnding};
-use crate::model::cursor::{Position2D, SelectionMode};
+use crate::model::cursor::{Cursor, Position2D, SelectionMode};
 use crate::model::event::{CursorId, Event};
-use crate::primitives::display_width::{byte_offset_at_visual_column, str_width};
+use crate::primitives::display_width::char_width;
 use crate::primitives::word_navigation::{
     find_word_end, find_word_start, find_word_start_left, find_word_start_right,
 };
@@ -43,26 +44,92 @@ fn pos_2d_to_byte(buffer: &Buffer, pos: Position2D) -> usize {
     line_start + clamped_col
 }
 
-/// Calculate the visual column (display width) at the cursor position.
-/// Returns (visual_column, byte_column_within_line).
+/// Display width of `ch` when it starts at visual column `col`.
+/// Tabs expand to the next multiple of `tab_size`, matching the renderer.
+fn char_visual_width(ch: char, col: usize, tab_size: usize) -> usize {
+    if ch == '\t' {
+        tab_size - (col % tab_size)
+    } else {
+        char_width(ch)
+    }
+}
+
+/// Calculate the visual column (display width, tabs expanded) at the cursor position.
 fn calculate_visual_column(
     buffer: &mut Buffer,
     cursor_position: usize,
     estimated_line_length: usize,
-) -> (usize, usize) {
+    tab_size: usize,
+) -> usize {
     let mut iter = buffer.line_iterator(cursor_position, estimated_line_length);
     let current_line_start = iter.current_position();
     let byte_column = cursor_position.saturating_sub(current_line_start);
 
-    if let Some((_, line_content)) = iter.next() {
-        if byte_column > 0 && byte_column <= line_content.len() {
-            (str_width(&line_content[..byte_column]), byte_column)
-        } else {
-            (byte_column, byte_column) // Fallback for edge cases
-        }
+    match iter.next() {
+        Some((_, line_content)) if line_content.is_char_boundary(byte_column) => line_content
+            [..byte_column]
+            .chars()
+            .fold(0, |col, ch| col + char_visual_width(ch, col, tab_size)),
+        _ => byte_column, // Fallback for edge cases
+    }
+}
+
+/// The visual column vertical movement aims for: the cursor's remembered goal
+/// column if one is set, otherwise the column it currently sits at.
+fn goal_visual_column(
+    buffer: &mut Buffer,
+    cursor: &Cursor,
+    estimated_line_length: usize,
+    tab_size: usize,
+) -> usize {
+    if cursor.sticky_column > 0 {
+        cursor.sticky_column
     } else {
-        (byte_column, byte_column) // Fallback
+        calculate_visual_column(buffer, cursor.position, estimated_line_length, tab_size)
+    }
+}
+
+/// Byte position on the line starting at `line_start` for the first character
+/// at or past `goal_column`. Lines shorter than the goal clamp to their end
+/// (before the line ending), and zero-width characters are never split from
+/// their base character.
+fn position_at_visual_column(
+    line_start: usize,
+    line_content: &str,
+    goal_column: usize,
+    tab_size: usize,
+) -> usize {
+    let text = line_content.trim_end_matches(LINE_ENDING_CHARS);
+    let mut col = 0;
+    for (byte_idx, ch) in text.char_indices() {
+        let width = char_visual_width(ch, col, tab_size);
+        if col >= goal_column && width > 0 {
+            return line_start + byte_idx;
+        }
+        col += width;
     }
+    line_start + text.len()
+}
+
+/// Where a cursor moved vertically to the line starting at `line_start`
+/// lands, and the goal column it keeps
+pub(crate) fn vertical_move_target(
+    buffer: &mut Buffer,
+    cursor: &Cursor,
+    line_start: usize,
+    estimated_line_length: usize,
+    tab_size: usize,
+) -> (usize, usize) {
+    let goal_column = goal_visual_column(buffer, cursor, estimated_line_length, tab_size);
+    let line_content = buffer
+        .line_iterator(line_start, estimated_line_length)
+        .next()
+        .map(|(_, content)| content)
+        .unwrap_or_default();
+    (
+        position_at_visual_column(line_start, &line_content, goal_column, tab_size),
+        goal_column,
+    )
 }
 
 /// Pattern for matching line ending characters (\r and \n)
@@ -186,6 +253,29 @@ fn calculate_leading_whitespace_removal(
     }
 }
 
+/// Whether `position` is at the start of a line
+fn is_line_start(buffer: &Buffer, position: usize) -> bool {
+    position == 0 || buffer.slice_bytes(position - 1..position).first() == Some(&b'\n')
+}
+
+/// Select `range` with the anchor at its start and the cursor at its end
+fn add_select_range_event(
+    events: &mut Vec<Event>,
+    cursor_id: CursorId,
+    cursor: &Cursor,
+    range: Range<usize>,
+) {
+    events.push(Event::MoveCursor {
+        cursor_id,
+        old_position: cursor.position,
+        new_position: range.end,
+        old_anchor: cursor.anchor,
+        new_anchor: Some(range.start),
+        old_sticky_column: cursor.sticky_column,
+        new_sticky_column: 0, // Reset sticky column
+    });
+}
+
 /// Add a MoveCursor event to restore cursor position after indent/dedent
 fn add_move_cursor_event(
     events: &mut Vec<Event>,
@@ -334,29 +424,53 @@ fn get_auto_close_char(ch: char, auto_indent: bool) -> Option<char> {
     }
 }
 
-/// Calculate the correct indent for a closing delimiter using tree-sitter.
+/// Calculate the correct indent for a closing delimiter, from the language's indent
+/// rules or else using tree-sitter.
+///
+/// Returns None if the indent rules don't dedent this delimiter.
 fn calculate_closing_delimiter_indent(
     state: &mut EditorState,
     insert_position: usize,
     ch: char,
     tab_size: usize,
-) -> usize {
-    if let Some(language) = state.highlighter.language() {
-        state
-            .indent_calculator
-            .borrow_mut()
-            .calculate_dedent_for_delimiter(&state.buffer, insert_position, ch, language, tab_size)
-            .unwrap_or(0)
+) -> Option<usize> {
+    if let Some(rules) = &state.indent_rules {
+        rules.closing_indent(&state.buffer, insert_position, ch, state.tab_size)
+    } else if let Some(language) = state.highlighter.language() {
+        Some(
+            state
+                .indent_calculator
+                .borrow_mut()
+                .calculate_dedent_for_delimiter(
+                    &state.buffer,
+                    insert_position,
+                    ch,
+                    language,
+                    tab_size,
+                )
+                .unwrap_or(0),
+        )
     } else {
-        0
+        Some(0)
     }
 }
 
+/// Indent string for a line of the buffer, in the buffer's indent style
+fn buffer_indent_string(state: &EditorState, indent_width: usize, tab_size: usize) -> String {
+    // Indent rules measure in the buffer's own tab size
+    let tab_size = if state.indent_rules.is_some() {
+        state.tab_size
+    } else {
+        tab_size
+    };
+    indent_to_string(indent_width, state.use_tabs, tab_size)
+}
+
 /// Convert a visual indent width to actual indent characters.
 /// When `use_tabs` is true, uses tab characters; otherwise uses spaces.
 /// The `indent_width` is the visual width in columns, and `tab_size` is
 /// how many columns a tab character represents.
-fn indent_to_string(indent_width: usize, use_tabs: bool, tab_size: usize) -> String {
+pub(crate) fn indent_to_string(indent_width: usize, use_tabs: bool, tab_size: usize) -> String {
     if use_tabs && tab_size > 0 {
         let num_tabs = indent_width / tab_size;
         let remaining_spaces = indent_width % tab_size;
@@ -370,6 +484,31 @@ fn indent_to_string(indent_width: usize, use_tabs: bool, tab_size: usize) -> Str
     }
 }
 
+/// Indent width the auto-indent engine gives a line started by Enter at
+/// `position`
+pub(crate) fn new_line_indent(state: &EditorState, position: usize, tab_size: usize) -> usize {
+    if let Some(rules) = &state.indent_rules {
+        // Per-language indent rules take precedence over tree-sitter
+        return rules
+            .newline_indent(&state.buffer, position, position, state.tab_size)
+            .indent;
+    }
+    if let Some(language) = state.highlighter.language() {
+        // Use tree-sitter-based indent when we have a highlighter
+        return state
+            .indent_calculator
+            .borrow_mut()
+            .calculate_indent(&state.buffer, position, language, tab_size)
+            .unwrap_or(0);
+    }
+    // Fallback for files without syntax highlighting (e.g., .txt)
+    crate::primitives::indent::IndentCalculator::calculate_indent_no_language(
+        &state.buffer,
+        position,
+        tab_size,
+    )
+}
+
 /// Handle skip-over with dedent: when typing a closing delimiter that exists after cursor,
 /// and the line has incorrect indentation, fix the indent and skip over.
 /// Returns true if handled (caller should continue to next cursor).
@@ -382,23 +521,28 @@ fn handle_skip_over_with_dedent(
     line_start: usize,
     tab_size: usize,
 ) -> bool {
-    let correct_indent = calculate_closing_delimiter_indent(state, insert_position, ch, tab_size);
-    let current_indent = insert_position - line_start;
+    let Some(correct_indent) =
+        calculate_closing_delimiter_indent(state, insert_position, ch, tab_size)
+    else {
+        return false;
+    };
+    let correct_indent = buffer_indent_string(state, correct_indent, tab_size);
+    let current_indent = state.get_text_range(line_start, insert_position);
 
     if current_indent != correct_indent {
         // Delete incorrect spacing
-        let deleted_text = state.get_text_range(line_start, insert_position);
         events.push(Event::Delete {
             range: line_start..insert_position,
-            deleted_text,
+            deleted_text: current_indent,
             cursor_id,
         });
 
         // Insert correct spacing
-        if correct_indent > 0 {
+        let indent_len = correct_indent.len();
+        if indent_len > 0 {
             events.push(Event::Insert {
                 position: line_start,
-                text: " ".repeat(correct_indent),
+                text: correct_indent,
                 cursor_id,
             });
         }
@@ -406,8 +550,8 @@ fn handle_skip_over_with_dedent(
         // Move cursor to after the closing delimiter
         events.push(Event::MoveCursor {
             cursor_id,
-            old_position: line_start + correct_indent,
-            new_position: line_start + correct_indent + 1,
+            old_position: line_start + indent_len,
+            new_position: line_start + indent_len + 1,
             old_anchor: None,
             new_anchor: None,
             old_sticky_column: 0,
@@ -433,6 +577,7 @@ fn handle_skip_over(events: &mut Vec<Event>, cursor_id: CursorId, insert_positio
 
 /// Handle auto-dedent: when typing a closing delimiter on a line with only spaces,
 /// fix the indentation and insert the delimiter.
+/// Returns true if handled (caller should continue to next cursor).
 fn handle_auto_dedent(
     state: &mut EditorState,
     events: &mut Vec<Event>,
@@ -441,8 +586,12 @@ fn handle_auto_dedent(
     insert_position: usize,
     line_start: usize,
     tab_size: usize,
-) {
-    let correct_indent = calculate_closing_delimiter_indent(state, insert_position, ch, tab_size);
+) -> bool {
+    let Some(correct_indent) =
+        calculate_closing_delimiter_indent(state, insert_position, ch, tab_size)
+    else {
+        return false;
+    };
 
     // Delete the incorrect spacing
     let spaces_to_delete = insert_position - line_start;
@@ -456,13 +605,50 @@ fn handle_auto_dedent(
     }
 
     // Insert correct spacing + the closing delimiter
-    let mut text = " ".repeat(correct_indent);
+    let mut text = buffer_indent_string(state, correct_indent, tab_size);
     text.push(ch);
     events.push(Event::Insert {
         position: line_start,
         text,
         cursor_id,
     });
+    true
+}
+
+/// Handle rule-based dedent: when typing makes the line match one of the language's
+/// "decrease indent" patterns (e.g. the `:` of `else:`), move the line left.
+/// Returns true if handled (caller should continue to next cursor).
+fn handle_rule_dedent(
+    state: &mut EditorState,
+    events: &mut Vec<Event>,
+    cursor_id: CursorId,
+    ch: char,
+    insert_position: usize,
+    line_start: usize,
+) -> bool {
+    let Some(rules) = &state.indent_rules else {
+        return false;
+    };
+    let Some(indent) = rules.dedent_on_type(&state.buffer, insert_position, ch, state.tab_size)
+    else {
+        return false;
+    };
+
+    let line_before_cursor = state.get_text_range(line_start, insert_position);
+    let mut text = indent_to_string(indent, state.use_tabs, state.tab_size);
+    text.push_str(line_before_cursor.trim_start());
+    text.push(ch);
+    events.push(Event::Delete {
+        range: line_start..insert_position,
+        deleted_text: line_before_cursor,
+        cursor_id,
+    });
+    events.push(Event::Insert {
+        position: line_start,
+        text,
+        cursor_id,
+    });
+    true
 }
 
 /// Check if auto-close should happen based on character after cursor.
@@ -593,6 +779,8 @@ fn insert_char_events(
     let cursor_data = collect_insert_cursor_data(state);
 
     for data in cursor_data {
+        let has_selection = data.selection.is_some();
+
         // Delete selection if present
         if let (Some(range), Some(text)) = (data.selection, data.deleted_text) {
             events.push(Event::Delete {
@@ -635,8 +823,7 @@ fn insert_char_events(
             && auto_indent
             && data.only_spaces
             && data.insert_position > data.line_start
-        {
-            handle_auto_dedent(
+            && handle_auto_dedent(
                 state,
                 events,
                 data.cursor_id,
@@ -644,7 +831,23 @@ fn insert_char_events(
                 data.insert_position,
                 data.line_start,
                 tab_size,
-            );
+            )
+        {
+            continue;
+        }
+
+        // Try rule-based dedent (e.g. `else:` in Python)
+        if auto_indent
+            && !has_selection
+            && handle_rule_dedent(
+                state,
+                events,
+                data.cursor_id,
+                ch,
+                data.insert_position,
+                data.line_start,
+            )
+        {
             continue;
         }
 
@@ -696,6 +899,9 @@ pub fn action_to_events(
     viewport_height: u16,
 ) -> Option<Vec<Event>> {
     let mut events = Vec::new();
+    // Vertical movement measures columns with the buffer's own tab width so the
+    // goal column matches what is rendered on screen
+    let tab_width = state.tab_size.max(1);
 
     match action {
         // Character input - insert at each cursor
@@ -721,11 +927,11 @@ pub fn action_to_events(
             let indent_positions: Vec<_> = cursor_vec
                 .iter()
                 .map(|(cursor_id, cursor)| {
-                    let indent_position = cursor
+                    let (indent_position, tail_position) = cursor
                         .selection_range()
-                        .map(|r| r.start)
-                        .unwrap_or(cursor.position);
-                    (*cursor_id, indent_position)
+                        .map(|r| (r.start, r.end))
+                        .unwrap_or((cursor.position, cursor.position));
+                    (*cursor_id, indent_position, tail_position)
                 })
                 .collect();
 
@@ -741,29 +947,46 @@ pub fn action_to_events(
 
             // Now process insertions
             let line_ending = state.buffer.line_ending().as_str();
-            for (cursor_id, indent_position) in indent_positions {
+            for (cursor_id, indent_position, tail_position) in indent_positions {
                 // Calculate indent for new line
                 let mut text = line_ending.to_string();
 
                 if auto_indent {
                     let use_tabs = state.use_tabs;
-                    if let Some(language) = state.highlighter.language() {
-                        // Use tree-sitter-based indent when we have a highlighter
-                        if let Some(indent_width) = state
-                            .indent_calculator
-                            .borrow_mut()
-                            .calculate_indent(&state.buffer, indent_position, language, tab_size)
-                        {
-                            text.push_str(&indent_to_string(indent_width, use_tabs, tab_size));
+                    if let Some(rules) = state.indent_rules.clone() {
+                        // Per-language indent rules take precedence over tree-sitter
+                        let tab_size = state.tab_size;
+                        let indent = rules.newline_indent(
+                            &state.buffer,
+                            indent_position,
+                            tail_position,
+                            tab_size,
+                        );
+                        text.push_str(&indent_to_string(indent.indent, use_tabs, tab_size));
+                        if let Some(closing_indent) = indent.closing_indent {
+                            // Move the closing bracket to its own line and put the
+                            // cursor on the indented line in between
+                            let cursor_position = indent_position + text.len();
+                            text.push_str(line_ending);
+                            text.push_str(&indent_to_string(closing_indent, use_tabs, tab_size));
+                            events.push(Event::Insert {
+                                position: indent_position,
+                                text: text.clone(),
+                                cursor_id,
+                            });
+                            events.push(Event::MoveCursor {
+                                cursor_id,
+                                old_position: indent_position + text.len(),
+                                new_position: cursor_position,
+                                old_anchor: None,
+                                new_anchor: None,
+                                old_sticky_column: 0,
+                                new_sticky_column: 0,
+                            });
+                            continue;
                         }
                     } else {
-                        // Fallback for files without syntax highlighting (e.g., .txt)
-                        let indent_width =
-                            crate::primitives::indent::IndentCalculator::calculate_indent_no_language(
-                                &state.buffer,
-                                indent_position,
-                                tab_size,
-                            );
+                        let indent_width = new_line_indent(state, indent_position, tab_size);
                         text.push_str(&indent_to_string(indent_width, use_tabs, tab_size));
                     }
                 }
@@ -1053,19 +1276,9 @@ pub fn action_to_events(
 
         Action::MoveUp => {
             for (cursor_id, cursor) in state.cursors.iter() {
-                // Calculate visual column first (iterator is dropped after this call)
-                let (current_visual_column, _) = calculate_visual_column(
-                    &mut state.buffer,
-                    cursor.position,
-                    estimated_line_length,
-                );
-
-                // Use sticky_column if set (now stores visual column), otherwise use current visual column
-                let goal_visual_column = if cursor.sticky_column > 0 {
-                    cursor.sticky_column
-                } else {
-                    current_visual_column
-                };
+                // Calculate the goal column first (iterator is dropped after this call)
+                let goal_column =
+                    goal_visual_column(&mut state.buffer, cursor, estimated_line_length, tab_width);
 
                 // Now create iterator for navigation
                 let mut iter = state
@@ -1073,11 +1286,12 @@ pub fn action_to_events(
                     .line_iterator(cursor.position, estimated_line_length);
 
                 if let Some((prev_line_start, prev_line_content)) = iter.prev() {
-                    // Calculate byte offset from visual column, ensuring valid character boundary
-                    let prev_line_text = prev_line_content.trim_end_matches('\n');
-                    let byte_offset =
-                        byte_offset_at_visual_column(prev_line_text, goal_visual_column);
-                    let new_pos = prev_line_start + byte_offset;
+                    let new_pos = position_at_visual_column(
+                        prev_line_start,
+                        &prev_line_content,
+                        goal_column,
+                        tab_width,
+                    );
 
                     // Preserve anchor if deselect_on_move is false (Emacs mark mode)
                     let new_anchor = if cursor.deselect_on_move {
@@ -1092,7 +1306,7 @@ pub fn action_to_events(
                         old_anchor: cursor.anchor,
                         new_anchor,
                         old_sticky_column: cursor.sticky_column,
-                        new_sticky_column: goal_visual_column, // Preserve the goal visual column
+                        new_sticky_column: goal_column, // Preserve the goal column
                     });
                 }
             }
@@ -1100,19 +1314,9 @@ pub fn action_to_events(
 
         Action::MoveDown => {
             for (cursor_id, cursor) in state.cursors.iter() {
-                // Calculate visual column first (iterator is dropped after this call)
-                let (current_visual_column, _) = calculate_visual_column(
-                    &mut state.buffer,
-                    cursor.position,
-                    estimated_line_length,
-                );
-
-                // Use sticky_column if set (now stores visual column), otherwise use current visual column
-                let goal_visual_column = if cursor.sticky_column > 0 {
-                    cursor.sticky_column
-                } else {
-                    current_visual_column
-                };
+                // Calculate the goal column first (iterator is dropped after this call)
+                let goal_column =
+                    goal_visual_column(&mut state.buffer, cursor, estimated_line_length, tab_width);
 
                 // Now create iterator for navigation
                 let mut iter = state
@@ -1123,11 +1327,12 @@ pub fn action_to_events(
                 iter.next();
 
                 if let Some((next_line_start, next_line_content)) = iter.next() {
-                    // Calculate byte offset from visual column, ensuring valid character boundary
-                    let next_line_text = next_line_content.trim_end_matches('\n');
-                    let byte_offset =
-                        byte_offset_at_visual_column(next_line_text, goal_visual_column);
-                    let new_pos = next_line_start + byte_offset;
+                    let new_pos = position_at_visual_column(
+                        next_line_start,
+                        &next_line_content,
+                        goal_column,
+                        tab_width,
+                    );
 
                     // Preserve anchor if deselect_on_move is false (Emacs mark mode)
                     let new_anchor = if cursor.deselect_on_move {
@@ -1142,7 +1347,7 @@ pub fn action_to_events(
                         old_anchor: cursor.anchor,
                         new_anchor,
                         old_sticky_column: cursor.sticky_column,
-                        new_sticky_column: goal_visual_column, // Preserve the goal visual column
+                        new_sticky_column: goal_column, // Preserve the goal column
                     });
                 }
             }
@@ -1289,24 +1494,21 @@ pub fn action_to_events(
             for (cursor_id, cursor) in state.cursors.iter() {
                 // Move up by viewport height
                 let lines_to_move = viewport_height.saturating_sub(1) as usize;
+                let goal_column =
+                    goal_visual_column(&mut state.buffer, cursor, estimated_line_length, tab_width);
                 let mut iter = state
                     .buffer
                     .line_iterator(cursor.position, estimated_line_length);
-                let current_line_start = iter.current_position();
-                let current_column = cursor.position - current_line_start;
-
-                // Use sticky_column if set, otherwise use current column
-                let goal_column = if cursor.sticky_column > 0 {
-                    cursor.sticky_column
-                } else {
-                    current_column
-                };
 
                 let mut new_pos = cursor.position;
                 for _ in 0..lines_to_move {
                     if let Some((line_start, line_content)) = iter.prev() {
-                        let line_len = line_content.trim_end_matches('\n').len();
-                        new_pos = line_start + goal_column.min(line_len);
+                        new_pos = position_at_visual_column(
+                            line_start,
+                            &line_content,
+                            goal_column,
+                            tab_width,
+                        );
                     } else {
                         new_pos = 0;
                         break;
@@ -1335,18 +1537,11 @@ pub fn action_to_events(
             for (cursor_id, cursor) in state.cursors.iter() {
                 // Move down by viewport height
                 let lines_to_move = viewport_height.saturating_sub(1) as usize;
+                let goal_column =
+                    goal_visual_column(&mut state.buffer, cursor, estimated_line_length, tab_width);
                 let mut iter = state
                     .buffer
                     .line_iterator(cursor.position, estimated_line_length);
-                let current_line_start = iter.current_position();
-                let current_column = cursor.position - current_line_start;
-
-                // Use sticky_column if set, otherwise use current column
-                let goal_column = if cursor.sticky_column > 0 {
-                    cursor.sticky_column
-                } else {
-                    current_column
-                };
 
                 // Consume current line
                 iter.next();
@@ -1354,8 +1549,12 @@ pub fn action_to_events(
                 let mut new_pos = cursor.position;
                 for _ in 0..lines_to_move {
                     if let Some((line_start, line_content)) = iter.next() {
-                        let line_len = line_content.trim_end_matches('\n').len();
-                        new_pos = line_start + goal_column.min(line_len);
+                        new_pos = position_at_visual_column(
+                            line_start,
+                            &line_content,
+                            goal_column,
+                            tab_width,
+                        );
                     } else {
                         // Reached end of buffer - clamp to last valid position
                         new_pos = max_cursor_position(&state.buffer);
@@ -1421,23 +1620,20 @@ pub fn action_to_events(
 
         Action::SelectUp => {
             for (cursor_id, cursor) in state.cursors.iter() {
+                let goal_column =
+                    goal_visual_column(&mut state.buffer, cursor, estimated_line_length, tab_width);
                 let mut iter = state
                     .buffer
                     .line_iterator(cursor.position, estimated_line_length);
-                let current_line_start = iter.current_position();
-                let current_column = cursor.position - current_line_start;
                 let anchor = cursor.anchor.unwrap_or(cursor.position);
 
-                // Use sticky_column if set, otherwise use current column
-                let goal_column = if cursor.sticky_column > 0 {
-                    cursor.sticky_column
-                } else {
-                    current_column
-                };
-
                 if let Some((prev_line_start, prev_line_content)) = iter.prev() {
-                    let prev_line_len = prev_line_content.trim_end_matches('\n').len();
-                    let new_pos = prev_line_start + goal_column.min(prev_line_len);
+                    let new_pos = position_at_visual_column(
+                        prev_line_start,
+                        &prev_line_content,
+                        goal_column,
+                        tab_width,
+                    );
 
                     events.push(Event::MoveCursor {
                         cursor_id,
@@ -1454,25 +1650,22 @@ pub fn action_to_events(
 
         Action::SelectDown => {
             for (cursor_id, cursor) in state.cursors.iter() {
+                let goal_column =
+                    goal_visual_column(&mut state.buffer, cursor, estimated_line_length, tab_width);
                 let mut iter = state
                     .buffer
                     .line_iterator(cursor.position, estimated_line_length);
-                let current_line_start = iter.current_position();
-                let current_column = cursor.position - current_line_start;
                 let anchor = cursor.anchor.unwrap_or(cursor.position);
 
-                // Use sticky_column if set, otherwise use current column
-                let goal_column = if cursor.sticky_column > 0 {
-                    cursor.sticky_column
-                } else {
-                    current_column
-                };
-
                 // Skip current line, then get next line
                 iter.next();
                 if let Some((next_line_start, next_line_content)) = iter.next() {
-                    let next_line_len = next_line_content.trim_end_matches('\n').len();
-                    let new_pos = next_line_start + goal_column.min(next_line_len);
+                    let new_pos = position_at_visual_column(
+                        next_line_start,
+                        &next_line_content,
+                        goal_column,
+                        tab_width,
+                    );
 
                     events.push(Event::MoveCursor {
                         cursor_id,
@@ -1599,25 +1792,22 @@ pub fn action_to_events(
         Action::SelectPageUp => {
             for (cursor_id, cursor) in state.cursors.iter() {
                 let lines_to_move = viewport_height.saturating_sub(1) as usize;
+                let goal_column =
+                    goal_visual_column(&mut state.buffer, cursor, estimated_line_length, tab_width);
                 let mut iter = state
                     .buffer
                     .line_iterator(cursor.position, estimated_line_length);
-                let current_line_start = iter.current_position();
-                let current_column = cursor.position - current_line_start;
                 let anchor = cursor.anchor.unwrap_or(cursor.position);
 
-                // Use sticky_column if set, otherwise use current column
-                let goal_column = if cursor.sticky_column > 0 {
-                    cursor.sticky_column
-                } else {
-                    current_column
-                };
-
                 let mut new_pos = cursor.position;
                 for _ in 0..lines_to_move {
                     if let Some((line_start, line_content)) = iter.prev() {
-                        let line_len = line_content.trim_end_matches('\n').len();
-                        new_pos = line_start + goal_column.min(line_len);
+                        new_pos = position_at_visual_column(
+                            line_start,
+                            &line_content,
+                            goal_column,
+                            tab_width,
+                        );
                     } else {
                         new_pos = 0;
                         break;
@@ -1639,28 +1829,25 @@ pub fn action_to_events(
         Action::SelectPageDown => {
             for (cursor_id, cursor) in state.cursors.iter() {
                 let lines_to_move = viewport_height.saturating_sub(1) as usize;
+                let goal_column =
+                    goal_visual_column(&mut state.buffer, cursor, estimated_line_length, tab_width);
                 let mut iter = state
                     .buffer
                     .line_iterator(cursor.position, estimated_line_length);
-                let current_line_start = iter.current_position();
-                let current_column = cursor.position - current_line_start;
                 let anchor = cursor.anchor.unwrap_or(cursor.position);
 
-                // Use sticky_column if set, otherwise use current column
-                let goal_column = if cursor.sticky_column > 0 {
-                    cursor.sticky_column
-                } else {
-                    current_column
-                };
-
                 // Consume current line
                 iter.next();
 
                 let mut new_pos = cursor.position;
                 for _ in 0..lines_to_move {
                     if let Some((line_start, line_content)) = iter.next() {
-                        let line_len = line_content.trim_end_matches('\n').len();
-                        new_pos = line_start + goal_column.min(line_len);
+                        new_pos = position_at_visual_column(
+                            line_start,
+                            &line_content,
+                            goal_column,
+                            tab_width,
+                        );
                     } else {
                         // Reached end of buffer - clamp to last valid position
                         new_pos = max_cursor_position(&state.buffer);
@@ -2099,10 +2286,28 @@ pub fn action_to_events(
         | Action::SaveAs
         | Action::Open
         | Action::SwitchProject
+        | Action::OpenRecentProject
+        | Action::TogglePinProject
+        | Action::RestoreSessionSnapshot
+        | Action::SaveLayoutAs
+        | Action::ScrollUpKeepCursor
+        | Action::ScrollDownKeepCursor
+        | Action::ScrollHalfPageUp
+        | Action::ScrollHalfPageDown
+        | Action::CursorLineToTop
+        | Action::CursorLineToCenter
+        | Action::CursorLineToBottom
+        | Action::SwitchLayout(_)
+        | Action::SwitchLayoutAt(_)
+        | Action::DeleteLayout
         | Action::New
         | Action::Close
         | Action::CloseTab
         | Action::GotoLine
+        | Action::GotoByteOffset
+        | Action::GotoPercentage
+        | Action::CopyDataPath
+        | Action::GotoDataPath
         | Action::NextBuffer
         | Action::PrevBuffer
         | Action::SwitchToPreviousTab
@@ -2118,6 +2323,11 @@ pub fn action_to_events(
         | Action::CopyWithTheme(_)
         | Action::Cut
         | Action::Paste
+        | Action::PasteReindent
+        | Action::PasteVerbatim
+        | Action::PasteFromHistory
+        | Action::CyclePaste
+        | Action::RetrySystemClipboard
         | Action::YankWordForward
         | Action::YankWordBackward
         | Action::YankToLineEnd
@@ -2129,19 +2339,38 @@ pub fn action_to_events(
         | Action::ShowHelp
         | Action::ToggleLineWrap
         | Action::ToggleComposeMode
+        | Action::ToggleDistractionFree
         | Action::SetComposeWidth
         | Action::IncreaseSplitSize
         | Action::DecreaseSplitSize
         | Action::ToggleMaximizeSplit
+        | Action::TogglePaneLock
         | Action::Undo
         | Action::Redo
         | Action::GoToMatchingBracket
         | Action::JumpToNextError
         | Action::JumpToPreviousError
         | Action::ShowKeyboardShortcuts
+        | Action::KeyboardShortcutsFilter
         | Action::ShowWarnings
+        | Action::ReviewBlockedCommands
+        | Action::CancelProgress
         | Action::ShowLspStatus
+        | Action::UpdateNow
+        | Action::ShowSessionOwnership
         | Action::ClearWarnings
+        | Action::RetryPluginInit
+        | Action::FocusNotifications
+        | Action::ShowNotificationHistory
+        | Action::ShowUsageStats
+        | Action::ExportUsageStats
+        | Action::ExportSettingsArchive
+        | Action::ImportSettingsArchive
+        | Action::ShowFullMessage
+        | Action::SelectionStatistics
+        | Action::OpenEditorLog
+        | Action::FilterEditorLog
+        | Action::OpenEditorLogAtEntry
         | Action::SmartHome
         | Action::ToggleComment
         | Action::SetBookmark(_)
@@ -2164,6 +2393,7 @@ pub fn action_to_events(
         | Action::PromptSetBookmark
         | Action::PromptJumpToBookmark
         | Action::PromptConfirm
+        | Action::PromptConfirmDryRun
         | Action::PromptCancel
         | Action::PromptBackspace
         | Action::PromptDelete
@@ -2218,17 +2448,26 @@ pub fn action_to_events(
         | Action::FileExplorerRename
         | Action::FileExplorerToggleHidden
         | Action::FileExplorerToggleGitignored
+        | Action::FileExplorerToggleDetails
+        | Action::FileExplorerCycleSort
+        | Action::FileExplorerShowAll
+        | Action::FileExplorerContextMenu
         | Action::LspCompletion
         | Action::LspGotoDefinition
         | Action::LspReferences
         | Action::LspRename
+        | Action::RenameInBuffer
         | Action::LspHover
         | Action::LspSignatureHelp
         | Action::LspCodeActions
+        | Action::BatchCodeAction
         | Action::LspRestart
         | Action::LspStop
         | Action::ToggleInlayHints
         | Action::ToggleMouseHover
+        | Action::ToggleInlineBlame
+        | Action::ToggleInlineBlameAll
+        | Action::ShowBlameCommit
         | Action::ToggleLineNumbers
         | Action::ToggleMouseCapture
         | Action::DumpConfig
@@ -2238,6 +2477,7 @@ pub fn action_to_events(
         | Action::FindPrevious
         | Action::FindSelectionNext
         | Action::FindSelectionPrevious
+        | Action::SearchInAllSplits
         | Action::Replace
         | Action::QueryReplace
         | Action::MenuActivate
@@ -2250,16 +2490,38 @@ pub fn action_to_events(
         | Action::MenuOpen(_)
         | Action::SwitchKeybindingMap(_)
         | Action::PluginAction(_)
+        | Action::RunCommand(..)
         | Action::None
         | Action::ScrollTabsLeft
         | Action::ScrollTabsRight
         | Action::SelectTheme
+        | Action::ReloadTheme
+        | Action::ToggleLightDarkTheme
+        | Action::CheckThemeContrast
         | Action::SelectKeybindingMap
         | Action::SelectCursorStyle
         | Action::SelectLocale
         | Action::Revert
+        | Action::RevertBufferToSaved
+        | Action::CompareBufferWith
+        | Action::CompareNextHunk
+        | Action::ComparePreviousHunk
+        | Action::CompareCopyHunkToLeft
+        | Action::CompareCopyHunkToRight
+        | Action::CompareWithClipboard
+        | Action::CompareDirectories
+        | Action::DirCompareOpen
+        | Action::DirCompareToggleIdentical
+        | Action::DirCompareRefresh
         | Action::ToggleAutoRevert
+        | Action::ToggleBufferAutoRevert
+        | Action::ToggleFollowTail
+        | Action::TogglePinBuffer
+        | Action::EditAnyway
+        | Action::UnlockProtectedFile
+        | Action::CloseSavedBuffers
         | Action::FormatBuffer
+        | Action::RunSaveActions
         | Action::OpenTerminal
         | Action::CloseTerminal
         | Action::FocusTerminal
@@ -2280,10 +2542,57 @@ pub fn action_to_events(
         | Action::SetLineEnding
         | Action::ToggleIndentationStyle
         | Action::ToggleTabIndicators
+        | Action::ToggleRenderWhitespace
+        | Action::ToggleAnsiColors
         | Action::ToggleDebugHighlights
+        | Action::ToggleInputLatencyHud
+        | Action::ShowInputLayers
+        | Action::FreeCaches
+        | Action::CompactBuffer
+        | Action::CompactAllBuffers
+        | Action::BufferMemoryReport
         | Action::ResetBufferSettings
         | Action::ShellCommand
-        | Action::ShellCommandReplace => return None,
+        | Action::ShellCommandReplace
+        | Action::InsertFile
+        | Action::InsertCommandOutput
+        | Action::ToggleCommandStdin
+        | Action::ToTitleCase
+        | Action::ToCamelCase
+        | Action::ToSnakeCase
+        | Action::SortLines
+        | Action::UniqueLines
+        | Action::UniqueAdjacentLines
+        | Action::ReverseLines
+        | Action::ShuffleLines
+        | Action::PasteAsColumn
+        | Action::AlignSelections
+        | Action::PendingEditsToggle
+        | Action::PendingEditsToggleFile
+        | Action::PendingEditsToggleExpand
+        | Action::PendingEditsApply
+        | Action::PendingEditsCancel
+        | Action::TodosList
+        | Action::TodosRefresh
+        | Action::TodosOpen
+        | Action::WorkspaceDiagnostics
+        | Action::WorkspaceDiagnosticsOpen
+        | Action::RemoteDirOpen
+        // Paragraph and sentence motions need the config's abbreviations
+        // and the buffer's language, see `Editor::prose_action_events`
+        | Action::MoveParagraphForward
+        | Action::MoveParagraphBackward
+        | Action::MoveSentenceForward
+        | Action::MoveSentenceBackward
+        | Action::SelectParagraphForward
+        | Action::SelectParagraphBackward
+        | Action::SelectSentenceForward
+        | Action::SelectSentenceBackward
+        | Action::SelectSentence
+        | Action::DeleteParagraphForward
+        | Action::DeleteParagraphBackward
+        | Action::DeleteSentenceForward
+        | Action::DeleteSentenceBackward => return None,
 
         // Block/rectangular selection actions
         Action::BlockSelectLeft => {
@@ -2305,6 +2614,16 @@ pub fn action_to_events(
         Action::SelectLine => {
             // Select the entire line for each cursor
             for (cursor_id, cursor) in state.cursors.iter() {
+                // Whole lines already selected: extend down by the next line
+                let extending = cursor.anchor.is_some_and(|anchor| {
+                    anchor < cursor.position
+                        && is_line_start(&state.buffer, anchor)
+                        && is_line_start(&state.buffer, cursor.position)
+                });
+                if extending && cursor.position >= state.buffer.len() {
+                    continue;
+                }
+
                 // Use iterator to get line bounds
                 let mut iter = state
                     .buffer
@@ -2312,13 +2631,14 @@ pub fn action_to_events(
                 if let Some((line_start, line_content)) = iter.next() {
                     // Include newline if present
                     let line_end = line_start + line_content.len();
+                    let anchor = if extending { cursor.anchor } else { None };
 
                     events.push(Event::MoveCursor {
                         cursor_id,
                         old_position: cursor.position,
                         new_position: line_end,
                         old_anchor: cursor.anchor,
-                        new_anchor: Some(line_start),
+                        new_anchor: Some(anchor.unwrap_or(line_start)),
                         old_sticky_column: cursor.sticky_column,
                         new_sticky_column: 0, // Reset sticky column
                     });
@@ -2326,8 +2646,83 @@ pub fn action_to_events(
             }
         }
 
+        Action::SelectParagraph => {
+            for (cursor_id, cursor) in state.cursors.iter() {
+                if let Some(range) = selection_expansion::paragraph_range(
+                    &mut state.buffer,
+                    cursor.position,
+                    estimated_line_length,
+                ) {
+                    add_select_range_event(&mut events, cursor_id, cursor, range);
+                }
+            }
+        }
+
+        Action::ExpandSelectionToIndentation => {
+            for (cursor_id, cursor) in state.cursors.iter() {
+                if let Some(range) = selection_expansion::indentation_block_range(
+                    &mut state.buffer,
+                    cursor.position,
+                    state.tab_size,
+                    estimated_line_length,
+                ) {
+                    add_select_range_event(&mut events, cursor_id, cursor, range);
+                }
+            }
+        }
+
         Action::ExpandSelection => {
-            // Expand selection for each cursor
+            let cursors = &state.cursors;
+            state
+                .selection_expansions
+                .retain(|cursor_id, _| cursors.get(*cursor_id).is_some());
+            for (cursor_id, cursor) in state.cursors.iter() {
+                if let Some((anchor, position)) = selection_expansion::expand(
+                    &mut state.selection_expansions,
+                    &mut state.buffer,
+                    cursor_id,
+                    cursor,
+                    state.tab_size,
+                    estimated_line_length,
+                ) {
+                    add_select_range_event(&mut events, cursor_id, cursor, anchor..position);
+                }
+            }
+        }
+
+        Action::SelectDataValue => {
+            if let Some(outline) = state.data_outline.get(&state.buffer) {
+                for (cursor_id, cursor) in state.cursors.iter() {
+                    let selection = cursor
+                        .selection_range()
+                        .unwrap_or(cursor.position..cursor.position);
+                    if let Some(range) = outline.value_range_around(selection) {
+                        add_select_range_event(&mut events, cursor_id, cursor, range);
+                    }
+                }
+            }
+        }
+
+        Action::ShrinkSelection => {
+            for (cursor_id, cursor) in state.cursors.iter() {
+                if let Some((anchor, position)) =
+                    selection_expansion::shrink(&mut state.selection_expansions, cursor_id, cursor)
+                {
+                    events.push(Event::MoveCursor {
+                        cursor_id,
+                        old_position: cursor.position,
+                        new_position: position,
+                        old_anchor: cursor.anchor,
+                        new_anchor: anchor,
+                        old_sticky_column: cursor.sticky_column,
+                        new_sticky_column: 0, // Reset sticky column
+                    });
+                }
+            }
+        }
+
+        Action::ExtendSelectionByWord => {
+            // Extend selection by a word to the right for each cursor
             for (cursor_id, cursor) in state.cursors.iter() {
                 if let Some(anchor) = cursor.anchor {
                     // Already have a selection - expand by one word to the right
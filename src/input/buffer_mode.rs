@@ -24,6 +24,9 @@ pub struct BufferMode {
 
     /// Whether buffers with this mode are read-only by default
     pub read_only: bool,
+
+    /// Plugin that defined this mode (None for built-in modes)
+    pub plugin: Option<String>,
 }
 
 impl BufferMode {
@@ -35,6 +38,7 @@ impl BufferMode {
             keybindings: HashMap::new(),
             chord_keybindings: HashMap::new(),
             read_only: false,
+            plugin: None,
         }
     }
 
@@ -71,6 +75,12 @@ impl BufferMode {
         self
     }
 
+    /// Record the plugin that defined this mode
+    pub fn with_plugin(mut self, plugin: Option<String>) -> Self {
+        self.plugin = plugin;
+        self
+    }
+
     /// Add multiple keybindings at once
     pub fn with_bindings(mut self, bindings: Vec<(KeyCode, KeyModifiers, String)>) -> Self {
         for (code, modifiers, command) in bindings {
@@ -106,6 +116,88 @@ impl ModeRegistry {
 
         registry.register(special_mode);
 
+        // Built-in mode for the pending edits review buffer
+        let pending_edits_mode = BufferMode::new("pending-edits")
+            .with_parent("special")
+            .with_binding(
+                KeyCode::Char(' '),
+                KeyModifiers::NONE,
+                "pending_edits_toggle",
+            )
+            .with_binding(
+                KeyCode::Char('f'),
+                KeyModifiers::NONE,
+                "pending_edits_toggle_file",
+            )
+            .with_binding(
+                KeyCode::Tab,
+                KeyModifiers::NONE,
+                "pending_edits_toggle_expand",
+            )
+            .with_binding(KeyCode::Enter, KeyModifiers::NONE, "pending_edits_apply")
+            .with_binding(
+                KeyCode::Char('q'),
+                KeyModifiers::NONE,
+                "pending_edits_cancel",
+            )
+            .with_binding(KeyCode::Esc, KeyModifiers::NONE, "pending_edits_cancel");
+
+        registry.register(pending_edits_mode);
+
+        // Built-in mode for the TODO list buffer
+        let todos_mode = BufferMode::new("todos")
+            .with_parent("special")
+            .with_binding(KeyCode::Enter, KeyModifiers::NONE, "todos_open")
+            .with_binding(KeyCode::Char('g'), KeyModifiers::NONE, "todos_refresh");
+
+        registry.register(todos_mode);
+
+        // Built-in mode for the directory comparison buffer
+        let dir_compare_mode = BufferMode::new("dir-compare")
+            .with_parent("special")
+            .with_binding(KeyCode::Enter, KeyModifiers::NONE, "dir_compare_open")
+            .with_binding(
+                KeyCode::Char('i'),
+                KeyModifiers::NONE,
+                "dir_compare_toggle_identical",
+            )
+            .with_binding(
+                KeyCode::Char('g'),
+                KeyModifiers::NONE,
+                "dir_compare_refresh",
+            );
+
+        registry.register(dir_compare_mode);
+
+        // Built-in mode for the workspace diagnostics panel
+        let workspace_diagnostics_mode = BufferMode::new("workspace-diagnostics")
+            .with_parent("special")
+            .with_binding(
+                KeyCode::Enter,
+                KeyModifiers::NONE,
+                "workspace_diagnostics_open",
+            );
+
+        registry.register(workspace_diagnostics_mode);
+
+        // Built-in mode for remote directory listings
+        let remote_dir_mode = BufferMode::new("remote-dir")
+            .with_parent("special")
+            .with_binding(KeyCode::Enter, KeyModifiers::NONE, "remote_dir_open");
+
+        registry.register(remote_dir_mode);
+
+        // Built-in mode for the keyboard shortcuts view
+        let keyboard_shortcuts_mode = BufferMode::new("keyboard-shortcuts")
+            .with_parent("special")
+            .with_binding(
+                KeyCode::Char('/'),
+                KeyModifiers::NONE,
+                "keyboard_shortcuts_filter",
+            );
+
+        registry.register(keyboard_shortcuts_mode);
+
         registry
     }
 
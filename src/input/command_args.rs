@@ -0,0 +1,314 @@
+//! Typed parameters of plugin commands
+//!
+//! A plugin may declare the parameters of a command when registering it.
+//! Run from the command palette, the editor prompts for each one before the
+//! action runs; run from a key binding (`run_command`) or typed as
+//! `:Name value…` in the palette, the given values are checked here against
+//! the declared types. The action gets the values by name in `options.args`.
+
+use rust_i18n::t;
+use serde_json::{Map, Value};
+use std::path::Path;
+
+/// Type of a command parameter
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CommandArgType {
+    String,
+    Number,
+    /// A file or directory; relative paths are resolved against the working
+    /// directory
+    Path,
+    /// One of the listed values
+    Choice(Vec<String>),
+}
+
+impl CommandArgType {
+    /// Type named `name`: "string", "number", "path" or "choice" (which
+    /// takes `choices`)
+    pub fn from_name(name: &str, choices: Vec<String>) -> Option<Self> {
+        match name {
+            "string" => Some(Self::String),
+            "number" => Some(Self::Number),
+            "path" => Some(Self::Path),
+            "choice" if !choices.is_empty() => Some(Self::Choice(choices)),
+            _ => None,
+        }
+    }
+}
+
+/// A declared parameter of a command
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommandArg {
+    /// Key of the value in the action's `options.args`
+    pub name: String,
+    pub arg_type: CommandArgType,
+    /// Whether the parameter may be left out
+    pub optional: bool,
+}
+
+impl CommandArg {
+    /// Value of this parameter typed as `text`, or `None` if it was left
+    /// empty and is optional
+    pub fn parse(&self, text: &str, working_dir: &Path) -> Result<Option<Value>, String> {
+        let text = match self.arg_type {
+            CommandArgType::String => text,
+            _ => text.trim(),
+        };
+        if text.is_empty() {
+            return if self.optional {
+                Ok(None)
+            } else {
+                Err(t!("command_args.missing", name = &self.name).to_string())
+            };
+        }
+        let value = match &self.arg_type {
+            CommandArgType::String => Value::String(text.to_string()),
+            CommandArgType::Number => parse_number(text).ok_or_else(|| self.not_a_number(text))?,
+            CommandArgType::Path => {
+                Value::String(working_dir.join(text).to_string_lossy().into_owned())
+            }
+            CommandArgType::Choice(choices) => {
+                if !choices.iter().any(|choice| choice == text) {
+                    return Err(self.not_a_choice(choices, text));
+                }
+                Value::String(text.to_string())
+            }
+        };
+        Ok(Some(value))
+    }
+
+    /// Check a value given as JSON, as in a key binding's arguments
+    pub fn check(&self, value: &Value, working_dir: &Path) -> Result<Option<Value>, String> {
+        match (&self.arg_type, value) {
+            (_, Value::Null) => self.parse("", working_dir),
+            (CommandArgType::Number, Value::Number(_)) => Ok(Some(value.clone())),
+            (_, Value::String(text)) => self.parse(text, working_dir),
+            (CommandArgType::Number, _) => Err(self.not_a_number(&value.to_string())),
+            (CommandArgType::Choice(choices), _) => {
+                Err(self.not_a_choice(choices, &value.to_string()))
+            }
+            _ => Err(t!("command_args.not_text", name = &self.name).to_string()),
+        }
+    }
+
+    fn not_a_number(&self, value: &str) -> String {
+        t!(
+            "command_args.not_a_number",
+            name = &self.name,
+            value = value
+        )
+        .to_string()
+    }
+
+    fn not_a_choice(&self, choices: &[String], value: &str) -> String {
+        t!(
+            "command_args.not_a_choice",
+            name = &self.name,
+            value = value,
+            choices = choices.join(", ")
+        )
+        .to_string()
+    }
+}
+
+/// Number typed as `text`, kept an integer when it is one
+fn parse_number(text: &str) -> Option<Value> {
+    if let Ok(n) = text.parse::<i64>() {
+        return Some(Value::from(n));
+    }
+    let n = text.parse::<f64>().ok()?;
+    serde_json::Number::from_f64(n).map(Value::Number)
+}
+
+/// Values of `args` given by name, as in a key binding's arguments
+pub fn check_named(
+    args: &[CommandArg],
+    values: &Map<String, Value>,
+    working_dir: &Path,
+) -> Result<Map<String, Value>, String> {
+    if let Some(unknown) = values.keys().find(|k| !args.iter().any(|a| &a.name == *k)) {
+        return Err(t!("command_args.unknown", name = unknown).to_string());
+    }
+    let mut checked = Map::new();
+    for arg in args {
+        let value = values.get(&arg.name).unwrap_or(&Value::Null);
+        if let Some(value) = arg.check(value, working_dir)? {
+            checked.insert(arg.name.clone(), value);
+        }
+    }
+    Ok(checked)
+}
+
+/// Values of `args` given in order on a command line
+pub fn parse_positional(
+    args: &[CommandArg],
+    line: &str,
+    working_dir: &Path,
+) -> Result<Map<String, Value>, String> {
+    let words = split_words(line)?;
+    if words.len() > args.len() {
+        return Err(t!("command_args.too_many", count = args.len()).to_string());
+    }
+    let mut parsed = Map::new();
+    for (i, arg) in args.iter().enumerate() {
+        let word = words.get(i).map_or("", String::as_str);
+        if let Some(value) = arg.parse(word, working_dir)? {
+            parsed.insert(arg.name.clone(), value);
+        }
+    }
+    Ok(parsed)
+}
+
+/// Split a command line into words; single or double quotes keep spaces
+/// in a word
+pub fn split_words(line: &str) -> Result<Vec<String>, String> {
+    let mut words = Vec::new();
+    let mut word: Option<String> = None;
+    let mut quote = None;
+    for c in line.chars() {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => word.get_or_insert_with(String::new).push(c),
+            None if c == '"' || c == '\'' => {
+                quote = Some(c);
+                word.get_or_insert_with(String::new);
+            }
+            None if c.is_whitespace() => words.extend(word.take()),
+            None => word.get_or_insert_with(String::new).push(c),
+        }
+    }
+    if quote.is_some() {
+        return Err(t!("command_args.unclosed_quote").to_string());
+    }
+    words.extend(word);
+    Ok(words)
+}
+
+/// Split `line` into the longest of `names` it starts with and the rest,
+/// which must be empty or start with a space
+pub fn split_command_line<'a, 'n>(
+    line: &'a str,
+    names: impl IntoIterator<Item = &'n str>,
+) -> Option<(&'n str, &'a str)> {
+    names
+        .into_iter()
+        .filter(|name| {
+            line.strip_prefix(name)
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with(char::is_whitespace))
+        })
+        .max_by_key(|name| name.len())
+        .map(|name| (name, &line[name.len()..]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn args() -> Vec<CommandArg> {
+        vec![
+            CommandArg {
+                name: "who".to_string(),
+                arg_type: CommandArgType::String,
+                optional: false,
+            },
+            CommandArg {
+                name: "times".to_string(),
+                arg_type: CommandArgType::Number,
+                optional: true,
+            },
+        ]
+    }
+
+    #[test]
+    fn test_parse_by_type() {
+        let dir = Path::new("/project");
+        let number = &args()[1];
+        assert_eq!(number.parse("3", dir), Ok(Some(json!(3))));
+        assert_eq!(number.parse("2.5", dir), Ok(Some(json!(2.5))));
+        assert_eq!(number.parse(" ", dir), Ok(None));
+        assert!(number.parse("three", dir).unwrap_err().contains("three"));
+
+        let path = CommandArg {
+            name: "file".to_string(),
+            arg_type: CommandArgType::Path,
+            optional: false,
+        };
+        assert_eq!(
+            path.parse("src/main.rs", dir),
+            Ok(Some(json!(dir.join("src/main.rs").to_string_lossy())))
+        );
+        assert!(path.parse("", dir).is_err());
+
+        let choice = CommandArg {
+            name: "side".to_string(),
+            arg_type: CommandArgType::from_name("choice", vec!["left".into(), "right".into()])
+                .unwrap(),
+            optional: false,
+        };
+        assert_eq!(choice.parse("left", dir), Ok(Some(json!("left"))));
+        assert!(choice.parse("up", dir).unwrap_err().contains("left, right"));
+        assert_eq!(CommandArgType::from_name("choice", Vec::new()), None);
+        assert_eq!(CommandArgType::from_name("bool", Vec::new()), None);
+    }
+
+    #[test]
+    fn test_check_named() {
+        let dir = Path::new("/project");
+        let values = json!({"who": "Ada", "times": 2});
+        assert_eq!(
+            check_named(&args(), values.as_object().unwrap(), dir),
+            Ok(values.as_object().unwrap().clone())
+        );
+        let values = json!({"who": "Ada", "times": "2"});
+        assert_eq!(
+            check_named(&args(), values.as_object().unwrap(), dir).unwrap()["times"],
+            json!(2)
+        );
+        let values = json!({"who": 1});
+        assert!(check_named(&args(), values.as_object().unwrap(), dir).is_err());
+        let values = json!({"times": 1});
+        assert!(check_named(&args(), values.as_object().unwrap(), dir)
+            .unwrap_err()
+            .contains("who"));
+        let values = json!({"who": "Ada", "colour": "red"});
+        assert!(check_named(&args(), values.as_object().unwrap(), dir)
+            .unwrap_err()
+            .contains("colour"));
+    }
+
+    #[test]
+    fn test_parse_positional() {
+        let dir = Path::new("/project");
+        assert_eq!(
+            Value::Object(parse_positional(&args(), " 'Ada Lovelace' 4", dir).unwrap()),
+            json!({"who": "Ada Lovelace", "times": 4})
+        );
+        assert_eq!(
+            Value::Object(parse_positional(&args(), "Ada", dir).unwrap()),
+            json!({"who": "Ada"})
+        );
+        assert!(parse_positional(&args(), "", dir).is_err());
+        assert!(parse_positional(&args(), "Ada 1 2", dir).is_err());
+        assert!(parse_positional(&args(), "\"Ada", dir).is_err());
+        assert_eq!(
+            split_words(r#"a "" b"#),
+            Ok(vec!["a".into(), "".into(), "b".into()])
+        );
+    }
+
+    #[test]
+    fn test_split_command_line() {
+        let names = ["Greet", "Greet All", "Save"];
+        assert_eq!(
+            split_command_line("Greet All Ada", names),
+            Some(("Greet All", " Ada"))
+        );
+        assert_eq!(
+            split_command_line("Greet Ada", names),
+            Some(("Greet", " Ada"))
+        );
+        assert_eq!(split_command_line("Save", names), Some(("Save", "")));
+        assert_eq!(split_command_line("Greeter", names), None);
+    }
+}
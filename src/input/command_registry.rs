@@ -3,10 +3,13 @@
 //! This module allows plugins to register custom commands dynamically
 //! while maintaining the built-in command set.
 
+use crate::input::command_args::CommandArg;
 use crate::input::commands::{get_all_commands, Command, Suggestion};
 use crate::input::fuzzy::fuzzy_match;
 use crate::input::keybindings::Action;
 use crate::input::keybindings::KeyContext;
+use rust_i18n::t;
+use std::collections::{HashMap, HashSet};
 use std::sync::{Arc, RwLock};
 
 /// Registry for managing editor commands
@@ -20,6 +23,12 @@ pub struct CommandRegistry {
     /// Plugin-registered commands (dynamically added/removed)
     plugin_commands: Arc<RwLock<Vec<Command>>>,
 
+    /// Plugin actions whose commands were registered as supporting dry runs
+    dry_run_actions: Arc<RwLock<HashSet<String>>>,
+
+    /// Parameters declared by plugin actions, collected before they run
+    action_args: Arc<RwLock<HashMap<String, Vec<CommandArg>>>>,
+
     /// Command usage history (most recent first)
     /// Used to sort command palette suggestions by recency
     command_history: Vec<String>,
@@ -34,6 +43,8 @@ impl CommandRegistry {
         Self {
             builtin_commands: get_all_commands(),
             plugin_commands: Arc::new(RwLock::new(Vec::new())),
+            dry_run_actions: Arc::new(RwLock::new(HashSet::new())),
+            action_args: Arc::new(RwLock::new(HashMap::new())),
             command_history: Vec::new(),
         }
     }
@@ -82,6 +93,49 @@ impl CommandRegistry {
         commands.push(command);
     }
 
+    /// Record whether a plugin action reports instead of acting when run as
+    /// a dry run
+    pub fn set_dry_run_support(&self, action_name: &str, supported: bool) {
+        let mut actions = self.dry_run_actions.write().unwrap();
+        if supported {
+            actions.insert(action_name.to_string());
+        } else {
+            actions.remove(action_name);
+        }
+    }
+
+    /// Whether running `action` as a dry run only reports what it would do
+    pub fn supports_dry_run(&self, action: &Action) -> bool {
+        match action {
+            Action::PluginAction(name) => self.dry_run_actions.read().unwrap().contains(name),
+            action => action.supports_dry_run(),
+        }
+    }
+
+    /// Record the parameters a plugin action takes (none for a plain action)
+    pub fn set_action_args(&self, action_name: &str, args: Vec<CommandArg>) {
+        let mut actions = self.action_args.write().unwrap();
+        if args.is_empty() {
+            actions.remove(action_name);
+        } else {
+            actions.insert(action_name.to_string(), args);
+        }
+    }
+
+    /// Parameters to collect before running `action`
+    pub fn action_args(&self, action: &Action) -> Vec<CommandArg> {
+        match action {
+            Action::PluginAction(name) => self
+                .action_args
+                .read()
+                .unwrap()
+                .get(name)
+                .cloned()
+                .unwrap_or_default(),
+            _ => Vec::new(),
+        }
+    }
+
     /// Unregister a command by name
     pub fn unregister(&self, name: &str) {
         let mut commands = self.plugin_commands.write().unwrap();
@@ -151,6 +205,11 @@ impl CommandRegistry {
                     keybinding_resolver.get_keybinding_for_action(&cmd.action, current_context);
                 let history_pos = self.history_position(&cmd.name);
 
+                let localized_desc = if self.supports_dry_run(&cmd.action) {
+                    format!("{} {}", localized_desc, t!("palette.dry_run_available"))
+                } else {
+                    localized_desc
+                };
                 let suggestion = Suggestion::with_source(
                     localized_name,
                     Some(localized_desc),
@@ -694,4 +753,38 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn test_dry_run_support() {
+        let registry = CommandRegistry::new();
+        assert!(registry.supports_dry_run(&Action::CloseSavedBuffers));
+        assert!(!registry.supports_dry_run(&Action::Save));
+
+        let action = Action::PluginAction("my_plugin_cleanup".to_string());
+        assert!(!registry.supports_dry_run(&action));
+        registry.set_dry_run_support("my_plugin_cleanup", true);
+        assert!(registry.supports_dry_run(&action));
+        registry.set_dry_run_support("my_plugin_cleanup", false);
+        assert!(!registry.supports_dry_run(&action));
+    }
+
+    #[test]
+    fn test_action_args() {
+        use crate::input::command_args::CommandArgType;
+
+        let registry = CommandRegistry::new();
+        let action = Action::PluginAction("greet".to_string());
+        assert!(registry.action_args(&action).is_empty());
+
+        let args = vec![CommandArg {
+            name: "who".to_string(),
+            arg_type: CommandArgType::String,
+            optional: false,
+        }];
+        registry.set_action_args("greet", args.clone());
+        assert_eq!(registry.action_args(&action), args);
+        assert!(registry.action_args(&Action::Save).is_empty());
+        registry.set_action_args("greet", Vec::new());
+        assert!(registry.action_args(&action).is_empty());
+    }
 }
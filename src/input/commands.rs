@@ -1,6 +1,7 @@
 //! Command palette system for executing editor actions by name
 
 use crate::input::keybindings::{Action, KeyContext};
+use crate::services::plugins::manager::PLUGIN_RUNTIME_FAILED_CONTEXT;
 use rust_i18n::t;
 
 /// Source of a command (builtin or from a plugin)
@@ -173,6 +174,54 @@ pub fn get_all_commands() -> Vec<Command> {
             custom_contexts: vec![],
             source: CommandSource::Builtin,
         },
+        Command {
+            name: t!("cmd.open_recent_project").to_string(),
+            description: t!("cmd.open_recent_project_desc").to_string(),
+            action: Action::OpenRecentProject,
+            contexts: vec![],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: t!("cmd.toggle_pin_project").to_string(),
+            description: t!("cmd.toggle_pin_project_desc").to_string(),
+            action: Action::TogglePinProject,
+            contexts: vec![],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: t!("cmd.restore_session_snapshot").to_string(),
+            description: t!("cmd.restore_session_snapshot_desc").to_string(),
+            action: Action::RestoreSessionSnapshot,
+            contexts: vec![],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: t!("cmd.save_layout_as").to_string(),
+            description: t!("cmd.save_layout_as_desc").to_string(),
+            action: Action::SaveLayoutAs,
+            contexts: vec![],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: t!("cmd.switch_layout").to_string(),
+            description: t!("cmd.switch_layout_desc").to_string(),
+            action: Action::SwitchLayout(String::new()),
+            contexts: vec![],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: t!("cmd.delete_layout").to_string(),
+            description: t!("cmd.delete_layout_desc").to_string(),
+            action: Action::DeleteLayout,
+            contexts: vec![],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
         Command {
             name: t!("cmd.save_file").to_string(),
             description: t!("cmd.save_file_desc").to_string(),
@@ -213,6 +262,14 @@ pub fn get_all_commands() -> Vec<Command> {
             custom_contexts: vec![],
             source: CommandSource::Builtin,
         },
+        Command {
+            name: t!("cmd.close_saved_buffers").to_string(),
+            description: t!("cmd.close_saved_buffers_desc").to_string(),
+            action: Action::CloseSavedBuffers,
+            contexts: vec![],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
         Command {
             name: t!("cmd.revert_file").to_string(),
             description: t!("cmd.revert_file_desc").to_string(),
@@ -221,6 +278,70 @@ pub fn get_all_commands() -> Vec<Command> {
             custom_contexts: vec![],
             source: CommandSource::Builtin,
         },
+        Command {
+            name: t!("cmd.revert_buffer_to_saved").to_string(),
+            description: t!("cmd.revert_buffer_to_saved_desc").to_string(),
+            action: Action::RevertBufferToSaved,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: t!("cmd.compare_buffer_with").to_string(),
+            description: t!("cmd.compare_buffer_with_desc").to_string(),
+            action: Action::CompareBufferWith,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: t!("cmd.compare_with_clipboard").to_string(),
+            description: t!("cmd.compare_with_clipboard_desc").to_string(),
+            action: Action::CompareWithClipboard,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: t!("cmd.compare_directories").to_string(),
+            description: t!("cmd.compare_directories_desc").to_string(),
+            action: Action::CompareDirectories,
+            contexts: vec![],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: t!("cmd.compare_next_hunk").to_string(),
+            description: t!("cmd.compare_next_hunk_desc").to_string(),
+            action: Action::CompareNextHunk,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: t!("cmd.compare_previous_hunk").to_string(),
+            description: t!("cmd.compare_previous_hunk_desc").to_string(),
+            action: Action::ComparePreviousHunk,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: t!("cmd.compare_copy_hunk_to_left").to_string(),
+            description: t!("cmd.compare_copy_hunk_to_left_desc").to_string(),
+            action: Action::CompareCopyHunkToLeft,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: t!("cmd.compare_copy_hunk_to_right").to_string(),
+            description: t!("cmd.compare_copy_hunk_to_right_desc").to_string(),
+            action: Action::CompareCopyHunkToRight,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
         Command {
             name: t!("cmd.toggle_auto_revert").to_string(),
             description: t!("cmd.toggle_auto_revert_desc").to_string(),
@@ -229,6 +350,46 @@ pub fn get_all_commands() -> Vec<Command> {
             custom_contexts: vec![],
             source: CommandSource::Builtin,
         },
+        Command {
+            name: t!("cmd.toggle_buffer_auto_revert").to_string(),
+            description: t!("cmd.toggle_buffer_auto_revert_desc").to_string(),
+            action: Action::ToggleBufferAutoRevert,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: t!("cmd.toggle_follow_tail").to_string(),
+            description: t!("cmd.toggle_follow_tail_desc").to_string(),
+            action: Action::ToggleFollowTail,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: t!("cmd.toggle_pin_buffer").to_string(),
+            description: t!("cmd.toggle_pin_buffer_desc").to_string(),
+            action: Action::TogglePinBuffer,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: t!("cmd.edit_anyway").to_string(),
+            description: t!("cmd.edit_anyway_desc").to_string(),
+            action: Action::EditAnyway,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: t!("cmd.unlock_protected_file").to_string(),
+            description: t!("cmd.unlock_protected_file_desc").to_string(),
+            action: Action::UnlockProtectedFile,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
         Command {
             name: t!("cmd.format_buffer").to_string(),
             description: t!("cmd.format_buffer_desc").to_string(),
@@ -237,6 +398,14 @@ pub fn get_all_commands() -> Vec<Command> {
             custom_contexts: vec![],
             source: CommandSource::Builtin,
         },
+        Command {
+            name: t!("cmd.run_save_actions").to_string(),
+            description: t!("cmd.run_save_actions_desc").to_string(),
+            action: Action::RunSaveActions,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
         Command {
             name: t!("cmd.quit").to_string(),
             description: t!("cmd.quit_desc").to_string(),
@@ -294,6 +463,46 @@ pub fn get_all_commands() -> Vec<Command> {
             custom_contexts: vec![],
             source: CommandSource::Builtin,
         },
+        Command {
+            name: t!("cmd.paste_reindent").to_string(),
+            description: t!("cmd.paste_reindent_desc").to_string(),
+            action: Action::PasteReindent,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: t!("cmd.paste_verbatim").to_string(),
+            description: t!("cmd.paste_verbatim_desc").to_string(),
+            action: Action::PasteVerbatim,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: t!("cmd.paste_from_history").to_string(),
+            description: t!("cmd.paste_from_history_desc").to_string(),
+            action: Action::PasteFromHistory,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: t!("cmd.cycle_paste").to_string(),
+            description: t!("cmd.cycle_paste_desc").to_string(),
+            action: Action::CyclePaste,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: t!("cmd.retry_system_clipboard").to_string(),
+            description: t!("cmd.retry_system_clipboard_desc").to_string(),
+            action: Action::RetrySystemClipboard,
+            contexts: vec![],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
         Command {
             name: t!("cmd.delete_line").to_string(),
             description: t!("cmd.delete_line_desc").to_string(),
@@ -318,6 +527,38 @@ pub fn get_all_commands() -> Vec<Command> {
             custom_contexts: vec![],
             source: CommandSource::Builtin,
         },
+        Command {
+            name: t!("cmd.delete_sentence_forward").to_string(),
+            description: t!("cmd.delete_sentence_forward_desc").to_string(),
+            action: Action::DeleteSentenceForward,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: t!("cmd.delete_sentence_backward").to_string(),
+            description: t!("cmd.delete_sentence_backward_desc").to_string(),
+            action: Action::DeleteSentenceBackward,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: t!("cmd.delete_paragraph_forward").to_string(),
+            description: t!("cmd.delete_paragraph_forward_desc").to_string(),
+            action: Action::DeleteParagraphForward,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: t!("cmd.delete_paragraph_backward").to_string(),
+            description: t!("cmd.delete_paragraph_backward_desc").to_string(),
+            action: Action::DeleteParagraphBackward,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
         Command {
             name: t!("cmd.delete_to_end_of_line").to_string(),
             description: t!("cmd.delete_to_end_of_line_desc").to_string(),
@@ -350,6 +591,86 @@ pub fn get_all_commands() -> Vec<Command> {
             custom_contexts: vec![],
             source: CommandSource::Builtin,
         },
+        Command {
+            name: t!("cmd.transform_title_case").to_string(),
+            description: t!("cmd.transform_title_case_desc").to_string(),
+            action: Action::ToTitleCase,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: t!("cmd.transform_camel_case").to_string(),
+            description: t!("cmd.transform_camel_case_desc").to_string(),
+            action: Action::ToCamelCase,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: t!("cmd.transform_snake_case").to_string(),
+            description: t!("cmd.transform_snake_case_desc").to_string(),
+            action: Action::ToSnakeCase,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: t!("cmd.sort_lines").to_string(),
+            description: t!("cmd.sort_lines_desc").to_string(),
+            action: Action::SortLines,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: t!("cmd.unique_lines").to_string(),
+            description: t!("cmd.unique_lines_desc").to_string(),
+            action: Action::UniqueLines,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: t!("cmd.unique_adjacent_lines").to_string(),
+            description: t!("cmd.unique_adjacent_lines_desc").to_string(),
+            action: Action::UniqueAdjacentLines,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: t!("cmd.reverse_lines").to_string(),
+            description: t!("cmd.reverse_lines_desc").to_string(),
+            action: Action::ReverseLines,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: t!("cmd.shuffle_lines").to_string(),
+            description: t!("cmd.shuffle_lines_desc").to_string(),
+            action: Action::ShuffleLines,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: t!("cmd.paste_as_column").to_string(),
+            description: t!("cmd.paste_as_column_desc").to_string(),
+            action: Action::PasteAsColumn,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: t!("cmd.align_selections").to_string(),
+            description: t!("cmd.align_selections_desc").to_string(),
+            action: Action::AlignSelections,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
         Command {
             name: t!("cmd.open_line").to_string(),
             description: t!("cmd.open_line_desc").to_string(),
@@ -399,6 +720,22 @@ pub fn get_all_commands() -> Vec<Command> {
             custom_contexts: vec![],
             source: CommandSource::Builtin,
         },
+        Command {
+            name: t!("cmd.select_paragraph").to_string(),
+            description: t!("cmd.select_paragraph_desc").to_string(),
+            action: Action::SelectParagraph,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: t!("cmd.select_sentence").to_string(),
+            description: t!("cmd.select_sentence_desc").to_string(),
+            action: Action::SelectSentence,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
         Command {
             name: t!("cmd.expand_selection").to_string(),
             description: t!("cmd.expand_selection_desc").to_string(),
@@ -407,6 +744,38 @@ pub fn get_all_commands() -> Vec<Command> {
             custom_contexts: vec![],
             source: CommandSource::Builtin,
         },
+        Command {
+            name: t!("cmd.shrink_selection").to_string(),
+            description: t!("cmd.shrink_selection_desc").to_string(),
+            action: Action::ShrinkSelection,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: t!("cmd.selection_statistics").to_string(),
+            description: t!("cmd.selection_statistics_desc").to_string(),
+            action: Action::SelectionStatistics,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: t!("cmd.expand_selection_to_indentation").to_string(),
+            description: t!("cmd.expand_selection_to_indentation_desc").to_string(),
+            action: Action::ExpandSelectionToIndentation,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: t!("cmd.extend_selection_by_word").to_string(),
+            description: t!("cmd.extend_selection_by_word_desc").to_string(),
+            action: Action::ExtendSelectionByWord,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
         // Multi-cursor
         Command {
             name: t!("cmd.add_cursor_above").to_string(),
@@ -538,6 +907,14 @@ pub fn get_all_commands() -> Vec<Command> {
             custom_contexts: vec![],
             source: CommandSource::Builtin,
         },
+        Command {
+            name: t!("cmd.toggle_pane_lock").to_string(),
+            description: t!("cmd.toggle_pane_lock_desc").to_string(),
+            action: Action::TogglePaneLock,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
         // View toggles
         Command {
             name: t!("cmd.toggle_line_numbers").to_string(),
@@ -555,6 +932,54 @@ pub fn get_all_commands() -> Vec<Command> {
             custom_contexts: vec![],
             source: CommandSource::Builtin,
         },
+        Command {
+            name: t!("cmd.show_input_latency").to_string(),
+            description: t!("cmd.show_input_latency_desc").to_string(),
+            action: Action::ToggleInputLatencyHud,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: t!("cmd.free_caches").to_string(),
+            description: t!("cmd.free_caches_desc").to_string(),
+            action: Action::FreeCaches,
+            contexts: vec![],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: t!("cmd.compact_buffer").to_string(),
+            description: t!("cmd.compact_buffer_desc").to_string(),
+            action: Action::CompactBuffer,
+            contexts: vec![],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: t!("cmd.compact_all_buffers").to_string(),
+            description: t!("cmd.compact_all_buffers_desc").to_string(),
+            action: Action::CompactAllBuffers,
+            contexts: vec![],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: t!("cmd.buffer_memory_report").to_string(),
+            description: t!("cmd.buffer_memory_report_desc").to_string(),
+            action: Action::BufferMemoryReport,
+            contexts: vec![],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: t!("cmd.show_input_layers").to_string(),
+            description: t!("cmd.show_input_layers_desc").to_string(),
+            action: Action::ShowInputLayers,
+            contexts: vec![],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
         // Buffer settings commands
         Command {
             name: t!("cmd.set_tab_size").to_string(),
@@ -588,6 +1013,22 @@ pub fn get_all_commands() -> Vec<Command> {
             custom_contexts: vec![],
             source: CommandSource::Builtin,
         },
+        Command {
+            name: t!("cmd.toggle_render_whitespace").to_string(),
+            description: t!("cmd.toggle_render_whitespace_desc").to_string(),
+            action: Action::ToggleRenderWhitespace,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: t!("cmd.toggle_ansi_colors").to_string(),
+            description: t!("cmd.toggle_ansi_colors_desc").to_string(),
+            action: Action::ToggleAnsiColors,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
         Command {
             name: t!("cmd.reset_buffer_settings").to_string(),
             description: t!("cmd.reset_buffer_settings_desc").to_string(),
@@ -612,6 +1053,62 @@ pub fn get_all_commands() -> Vec<Command> {
             custom_contexts: vec![],
             source: CommandSource::Builtin,
         },
+        Command {
+            name: t!("cmd.scroll_up_keep_cursor").to_string(),
+            description: t!("cmd.scroll_up_keep_cursor_desc").to_string(),
+            action: Action::ScrollUpKeepCursor,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: t!("cmd.scroll_down_keep_cursor").to_string(),
+            description: t!("cmd.scroll_down_keep_cursor_desc").to_string(),
+            action: Action::ScrollDownKeepCursor,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: t!("cmd.scroll_half_page_up").to_string(),
+            description: t!("cmd.scroll_half_page_up_desc").to_string(),
+            action: Action::ScrollHalfPageUp,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: t!("cmd.scroll_half_page_down").to_string(),
+            description: t!("cmd.scroll_half_page_down_desc").to_string(),
+            action: Action::ScrollHalfPageDown,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: t!("cmd.cursor_line_to_top").to_string(),
+            description: t!("cmd.cursor_line_to_top_desc").to_string(),
+            action: Action::CursorLineToTop,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: t!("cmd.cursor_line_to_center").to_string(),
+            description: t!("cmd.cursor_line_to_center_desc").to_string(),
+            action: Action::CursorLineToCenter,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: t!("cmd.cursor_line_to_bottom").to_string(),
+            description: t!("cmd.cursor_line_to_bottom_desc").to_string(),
+            action: Action::CursorLineToBottom,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
         Command {
             name: t!("cmd.scroll_tabs_left").to_string(),
             description: t!("cmd.scroll_tabs_left_desc").to_string(),
@@ -733,6 +1230,38 @@ pub fn get_all_commands() -> Vec<Command> {
             custom_contexts: vec![],
             source: CommandSource::Builtin,
         },
+        Command {
+            name: t!("cmd.explorer_toggle_details").to_string(),
+            description: t!("cmd.explorer_toggle_details_desc").to_string(),
+            action: Action::FileExplorerToggleDetails,
+            contexts: vec![KeyContext::FileExplorer],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: t!("cmd.explorer_cycle_sort").to_string(),
+            description: t!("cmd.explorer_cycle_sort_desc").to_string(),
+            action: Action::FileExplorerCycleSort,
+            contexts: vec![KeyContext::FileExplorer],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: t!("cmd.explorer_load_all").to_string(),
+            description: t!("cmd.explorer_load_all_desc").to_string(),
+            action: Action::FileExplorerShowAll,
+            contexts: vec![KeyContext::FileExplorer],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: t!("cmd.explorer_context_menu").to_string(),
+            description: t!("cmd.explorer_context_menu_desc").to_string(),
+            action: Action::FileExplorerContextMenu,
+            contexts: vec![KeyContext::FileExplorer],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
         // View
         Command {
             name: t!("cmd.toggle_line_wrap").to_string(),
@@ -742,6 +1271,14 @@ pub fn get_all_commands() -> Vec<Command> {
             custom_contexts: vec![],
             source: CommandSource::Builtin,
         },
+        Command {
+            name: t!("cmd.toggle_distraction_free").to_string(),
+            description: t!("cmd.toggle_distraction_free_desc").to_string(),
+            action: Action::ToggleDistractionFree,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
         // Note: Compose mode commands removed - markdown_compose plugin provides these
         Command {
             name: t!("cmd.set_background").to_string(),
@@ -810,6 +1347,14 @@ pub fn get_all_commands() -> Vec<Command> {
             custom_contexts: vec![],
             source: CommandSource::Builtin,
         },
+        Command {
+            name: t!("cmd.search_in_all_splits").to_string(),
+            description: t!("cmd.search_in_all_splits_desc").to_string(),
+            action: Action::SearchInAllSplits,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
         Command {
             name: t!("cmd.replace").to_string(),
             description: t!("cmd.replace_desc").to_string(),
@@ -826,6 +1371,14 @@ pub fn get_all_commands() -> Vec<Command> {
             custom_contexts: vec![],
             source: CommandSource::Builtin,
         },
+        Command {
+            name: t!("cmd.apply_pending_edits").to_string(),
+            description: t!("cmd.apply_pending_edits_desc").to_string(),
+            action: Action::PendingEditsApply,
+            contexts: vec![],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
         // Navigation
         Command {
             name: t!("cmd.goto_line").to_string(),
@@ -835,6 +1388,46 @@ pub fn get_all_commands() -> Vec<Command> {
             custom_contexts: vec![],
             source: CommandSource::Builtin,
         },
+        Command {
+            name: t!("cmd.goto_byte_offset").to_string(),
+            description: t!("cmd.goto_byte_offset_desc").to_string(),
+            action: Action::GotoByteOffset,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: t!("cmd.goto_percentage").to_string(),
+            description: t!("cmd.goto_percentage_desc").to_string(),
+            action: Action::GotoPercentage,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: t!("cmd.copy_data_path").to_string(),
+            description: t!("cmd.copy_data_path_desc").to_string(),
+            action: Action::CopyDataPath,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: t!("cmd.goto_data_path").to_string(),
+            description: t!("cmd.goto_data_path_desc").to_string(),
+            action: Action::GotoDataPath,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: t!("cmd.select_data_value").to_string(),
+            description: t!("cmd.select_data_value_desc").to_string(),
+            action: Action::SelectDataValue,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
         Command {
             name: t!("cmd.smart_home").to_string(),
             description: t!("cmd.smart_home_desc").to_string(),
@@ -891,6 +1484,14 @@ pub fn get_all_commands() -> Vec<Command> {
             custom_contexts: vec![],
             source: CommandSource::Builtin,
         },
+        Command {
+            name: t!("cmd.batch_code_action").to_string(),
+            description: t!("cmd.batch_code_action_desc").to_string(),
+            action: Action::BatchCodeAction,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
         Command {
             name: t!("cmd.start_restart_lsp").to_string(),
             description: t!("cmd.start_restart_lsp_desc").to_string(),
@@ -973,6 +1574,39 @@ pub fn get_all_commands() -> Vec<Command> {
             custom_contexts: vec![],
             source: CommandSource::Builtin,
         },
+        // TODO comments
+        Command {
+            name: t!("cmd.todos_list").to_string(),
+            description: t!("cmd.todos_list_desc").to_string(),
+            action: Action::TodosList,
+            contexts: vec![],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: t!("cmd.todos_refresh").to_string(),
+            description: t!("cmd.todos_refresh_desc").to_string(),
+            action: Action::TodosRefresh,
+            contexts: vec![],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: t!("cmd.workspace_diagnostics").to_string(),
+            description: t!("cmd.workspace_diagnostics_desc").to_string(),
+            action: Action::WorkspaceDiagnostics,
+            contexts: vec![],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: t!("cmd.rename_in_buffer").to_string(),
+            description: t!("cmd.rename_in_buffer_desc").to_string(),
+            action: Action::RenameInBuffer,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
         // LSP
         Command {
             name: t!("cmd.rename_symbol").to_string(),
@@ -1072,6 +1706,22 @@ pub fn get_all_commands() -> Vec<Command> {
             custom_contexts: vec![],
             source: CommandSource::Builtin,
         },
+        Command {
+            name: t!("cmd.review_blocked_commands").to_string(),
+            description: t!("cmd.review_blocked_commands_desc").to_string(),
+            action: Action::ReviewBlockedCommands,
+            contexts: vec![],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: t!("cmd.cancel_progress").to_string(),
+            description: t!("cmd.cancel_progress_desc").to_string(),
+            action: Action::CancelProgress,
+            contexts: vec![],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
         Command {
             name: t!("cmd.show_lsp_status").to_string(),
             description: t!("cmd.show_lsp_status_desc").to_string(),
@@ -1080,6 +1730,22 @@ pub fn get_all_commands() -> Vec<Command> {
             custom_contexts: vec![],
             source: CommandSource::Builtin,
         },
+        Command {
+            name: t!("cmd.update_now").to_string(),
+            description: t!("cmd.update_now_desc").to_string(),
+            action: Action::UpdateNow,
+            contexts: vec![],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: t!("cmd.show_session_ownership").to_string(),
+            description: t!("cmd.show_session_ownership_desc").to_string(),
+            action: Action::ShowSessionOwnership,
+            contexts: vec![],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
         Command {
             name: t!("cmd.clear_warnings").to_string(),
             description: t!("cmd.clear_warnings_desc").to_string(),
@@ -1088,6 +1754,94 @@ pub fn get_all_commands() -> Vec<Command> {
             custom_contexts: vec![],
             source: CommandSource::Builtin,
         },
+        Command {
+            name: t!("cmd.focus_notifications").to_string(),
+            description: t!("cmd.focus_notifications_desc").to_string(),
+            action: Action::FocusNotifications,
+            contexts: vec![],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: t!("cmd.show_notification_history").to_string(),
+            description: t!("cmd.show_notification_history_desc").to_string(),
+            action: Action::ShowNotificationHistory,
+            contexts: vec![],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: t!("cmd.show_usage_stats").to_string(),
+            description: t!("cmd.show_usage_stats_desc").to_string(),
+            action: Action::ShowUsageStats,
+            contexts: vec![],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: t!("cmd.export_usage_stats").to_string(),
+            description: t!("cmd.export_usage_stats_desc").to_string(),
+            action: Action::ExportUsageStats,
+            contexts: vec![],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: t!("cmd.export_settings_archive").to_string(),
+            description: t!("cmd.export_settings_archive_desc").to_string(),
+            action: Action::ExportSettingsArchive,
+            contexts: vec![],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: t!("cmd.import_settings_archive").to_string(),
+            description: t!("cmd.import_settings_archive_desc").to_string(),
+            action: Action::ImportSettingsArchive,
+            contexts: vec![],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: t!("cmd.show_full_message").to_string(),
+            description: t!("cmd.show_full_message_desc").to_string(),
+            action: Action::ShowFullMessage,
+            contexts: vec![],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: t!("cmd.open_editor_log").to_string(),
+            description: t!("cmd.open_editor_log_desc").to_string(),
+            action: Action::OpenEditorLog,
+            contexts: vec![],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: t!("cmd.filter_editor_log").to_string(),
+            description: t!("cmd.filter_editor_log_desc").to_string(),
+            action: Action::FilterEditorLog,
+            contexts: vec![],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: t!("cmd.open_editor_log_at_entry").to_string(),
+            description: t!("cmd.open_editor_log_at_entry_desc").to_string(),
+            action: Action::OpenEditorLogAtEntry,
+            contexts: vec![],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: t!("cmd.retry_plugin_init").to_string(),
+            description: t!("cmd.retry_plugin_init_desc").to_string(),
+            action: Action::RetryPluginInit,
+            contexts: vec![],
+            custom_contexts: vec![PLUGIN_RUNTIME_FAILED_CONTEXT.to_string()],
+            source: CommandSource::Builtin,
+        },
         // Config
         Command {
             name: t!("cmd.dump_config").to_string(),
@@ -1105,6 +1859,31 @@ pub fn get_all_commands() -> Vec<Command> {
             custom_contexts: vec![],
             source: CommandSource::Builtin,
         },
+        // Git
+        Command {
+            name: t!("cmd.toggle_inline_blame").to_string(),
+            description: t!("cmd.toggle_inline_blame_desc").to_string(),
+            action: Action::ToggleInlineBlame,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: t!("cmd.toggle_inline_blame_all").to_string(),
+            description: t!("cmd.toggle_inline_blame_all_desc").to_string(),
+            action: Action::ToggleInlineBlameAll,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: t!("cmd.show_blame_commit").to_string(),
+            description: t!("cmd.show_blame_commit_desc").to_string(),
+            action: Action::ShowBlameCommit,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
         // Theme selection
         Command {
             name: t!("cmd.select_theme").to_string(),
@@ -1114,6 +1893,30 @@ pub fn get_all_commands() -> Vec<Command> {
             custom_contexts: vec![],
             source: CommandSource::Builtin,
         },
+        Command {
+            name: t!("cmd.reload_theme").to_string(),
+            description: t!("cmd.reload_theme_desc").to_string(),
+            action: Action::ReloadTheme,
+            contexts: vec![],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: t!("cmd.toggle_light_dark_theme").to_string(),
+            description: t!("cmd.toggle_light_dark_theme_desc").to_string(),
+            action: Action::ToggleLightDarkTheme,
+            contexts: vec![],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: t!("cmd.check_theme_contrast").to_string(),
+            description: t!("cmd.check_theme_contrast_desc").to_string(),
+            action: Action::CheckThemeContrast,
+            contexts: vec![],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
         // Keybinding map selection
         Command {
             name: t!("cmd.select_keybinding_map").to_string(),
@@ -1200,6 +2003,22 @@ pub fn get_all_commands() -> Vec<Command> {
             custom_contexts: vec![],
             source: CommandSource::Builtin,
         },
+        Command {
+            name: t!("cmd.insert_file").to_string(),
+            description: t!("cmd.insert_file_desc").to_string(),
+            action: Action::InsertFile,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
+        Command {
+            name: t!("cmd.insert_command_output").to_string(),
+            description: t!("cmd.insert_command_output_desc").to_string(),
+            action: Action::InsertCommandOutput,
+            contexts: vec![KeyContext::Normal],
+            custom_contexts: vec![],
+            source: CommandSource::Builtin,
+        },
     ]
 }
 
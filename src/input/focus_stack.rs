@@ -0,0 +1,233 @@
+//! Input focus stack
+//!
+//! Every UI surface that can take keys is an [`InputLayer`]. For each key
+//! event the editor builds a [`FocusStack`] of the surfaces that are open and
+//! offers the key to them top-down; each layer either consumes the key or
+//! passes it to the next one. The order comes from [`InputLayer::priority`]
+//! alone, so it doesn't depend on which component happens to check first:
+//!
+//! | Layer          | Takes                                                    |
+//! |----------------|----------------------------------------------------------|
+//! | notification   | every key while a notification has focus                 |
+//! | hover          | nothing: closes on any key and passes the key on         |
+//! | settings       | every key                                                |
+//! | menu           | every key                                                |
+//! | popup          | every key (action popups, code actions, ...)             |
+//! | prompt         | every key                                                |
+//! | completion     | the keys it uses; an auto-opened one passes the rest on  |
+//! | linked rename  | typing, Enter and Escape                                 |
+//! | terminal       | every key in terminal mode                               |
+//! | mode           | keys bound by the global plugin mode (`defineMode`)      |
+//! | buffer mode    | keys bound by the active buffer's mode                   |
+//! | editor         | the keybindings of the editor context                    |
+//!
+//! A popup sits above the prompt, so one opened while a prompt is active gets
+//! its keys; a completion popup belongs to the buffer and stays below it.
+//! Escape closes the topmost layer that [closes on
+//! Escape](InputLayer::closes_on_escape); settings and menus take Escape
+//! themselves, since it backs out of their nested dialogs first.
+
+use crate::input::keybindings::KeyContext;
+
+/// A UI surface that can take keys
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InputLayer {
+    /// A focused notification
+    Notification,
+    /// A hover or signature help popup
+    Hover,
+    /// The settings dialog
+    Settings,
+    /// An open menu
+    Menu,
+    /// A popup other than completion and hover
+    Popup,
+    /// The prompt, including the file browser and query-replace prompts
+    Prompt,
+    /// A completion popup
+    Completion,
+    /// Rename in buffer
+    LinkedRename,
+    /// A terminal buffer, in terminal mode or waiting to go back to it
+    Terminal,
+    /// The global mode set by a plugin, e.g. "vi-normal"
+    Mode(String),
+    /// The mode of the active buffer
+    BufferMode(String),
+    /// The keybindings of the editor context (normal, file explorer or
+    /// terminal)
+    Editor(KeyContext),
+}
+
+impl InputLayer {
+    /// Position in the stack; higher layers are offered keys first
+    pub fn priority(&self) -> u8 {
+        match self {
+            InputLayer::Notification => 120,
+            InputLayer::Hover => 110,
+            InputLayer::Settings => 100,
+            InputLayer::Menu => 90,
+            InputLayer::Popup => 80,
+            InputLayer::Prompt => 70,
+            InputLayer::Completion => 60,
+            InputLayer::LinkedRename => 50,
+            InputLayer::Terminal => 40,
+            InputLayer::Mode(_) => 30,
+            InputLayer::BufferMode(_) => 20,
+            InputLayer::Editor(_) => 10,
+        }
+    }
+
+    /// The keybinding context in effect while this layer is on top
+    ///
+    /// Layers without bindings of their own use `base`, the context of the
+    /// editor underneath.
+    pub fn key_context(&self, base: KeyContext) -> KeyContext {
+        match self {
+            InputLayer::Settings => KeyContext::Settings,
+            InputLayer::Menu => KeyContext::Menu,
+            InputLayer::Hover | InputLayer::Popup | InputLayer::Completion => KeyContext::Popup,
+            InputLayer::Prompt => KeyContext::Prompt,
+            InputLayer::Terminal => KeyContext::Terminal,
+            InputLayer::Editor(context) => *context,
+            InputLayer::Notification
+            | InputLayer::LinkedRename
+            | InputLayer::Mode(_)
+            | InputLayer::BufferMode(_) => base,
+        }
+    }
+
+    /// Whether Escape closes this layer instead of being offered to it
+    pub fn closes_on_escape(&self) -> bool {
+        matches!(
+            self,
+            InputLayer::Notification
+                | InputLayer::Popup
+                | InputLayer::Prompt
+                | InputLayer::Completion
+                | InputLayer::LinkedRename
+        )
+    }
+
+    /// Whether the layer's keys are resolved through the keymaps (see
+    /// [`crate::input::pipeline`]) rather than by the surface itself
+    pub fn is_keymap(&self) -> bool {
+        matches!(
+            self,
+            InputLayer::Mode(_) | InputLayer::BufferMode(_) | InputLayer::Editor(_)
+        )
+    }
+
+    /// Name shown by "Show Input Layers"
+    pub fn name(&self) -> String {
+        match self {
+            InputLayer::Notification => "notification".to_string(),
+            InputLayer::Hover => "hover".to_string(),
+            InputLayer::Settings => "settings".to_string(),
+            InputLayer::Menu => "menu".to_string(),
+            InputLayer::Popup => "popup".to_string(),
+            InputLayer::Prompt => "prompt".to_string(),
+            InputLayer::Completion => "completion".to_string(),
+            InputLayer::LinkedRename => "linked rename".to_string(),
+            InputLayer::Terminal => "terminal".to_string(),
+            InputLayer::Mode(mode) => format!("mode {}", mode),
+            InputLayer::BufferMode(mode) => format!("buffer mode {}", mode),
+            InputLayer::Editor(context) => format!("editor ({})", context.to_when_clause()),
+        }
+    }
+}
+
+/// The layers open for a key event, topmost first
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FocusStack {
+    layers: Vec<InputLayer>,
+}
+
+impl FocusStack {
+    /// Order `layers` by priority
+    pub fn new(mut layers: Vec<InputLayer>) -> Self {
+        layers.sort_by_key(|layer| std::cmp::Reverse(layer.priority()));
+        Self { layers }
+    }
+
+    /// The layers, topmost first
+    pub fn layers(&self) -> &[InputLayer] {
+        &self.layers
+    }
+
+    /// The topmost layer
+    pub fn top(&self) -> Option<&InputLayer> {
+        self.layers.first()
+    }
+
+    /// The highest layer below `priority`, for offering a key that a layer
+    /// passed on
+    pub fn next_below(&self, priority: u8) -> Option<&InputLayer> {
+        self.layers.iter().find(|layer| layer.priority() < priority)
+    }
+
+    /// The keybinding context of the topmost layer that has one
+    pub fn key_context(&self, base: KeyContext) -> KeyContext {
+        self.layers
+            .iter()
+            .find(|layer| !matches!(layer, InputLayer::Notification))
+            .map_or(base, |layer| layer.key_context(base))
+    }
+
+    /// The layers as "popup > prompt > editor (normal)"
+    pub fn describe(&self) -> String {
+        self.layers
+            .iter()
+            .map(InputLayer::name)
+            .collect::<Vec<_>>()
+            .join(" > ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_layers_ordered_by_priority() {
+        let stack = FocusStack::new(vec![
+            InputLayer::Editor(KeyContext::Normal),
+            InputLayer::Completion,
+            InputLayer::Mode("vi-normal".to_string()),
+            InputLayer::Prompt,
+            InputLayer::Popup,
+        ]);
+        assert_eq!(
+            stack.describe(),
+            "popup > prompt > completion > mode vi-normal > editor (normal)"
+        );
+        assert_eq!(stack.top(), Some(&InputLayer::Popup));
+        assert_eq!(
+            stack.next_below(InputLayer::Prompt.priority()),
+            Some(&InputLayer::Completion)
+        );
+        assert_eq!(
+            stack.next_below(InputLayer::Editor(KeyContext::Normal).priority()),
+            None
+        );
+    }
+
+    #[test]
+    fn test_key_context_comes_from_topmost_layer() {
+        let base = KeyContext::FileExplorer;
+        let stack = FocusStack::new(vec![InputLayer::Prompt, InputLayer::Completion]);
+        assert_eq!(stack.key_context(base), KeyContext::Prompt);
+        let stack = FocusStack::new(vec![InputLayer::Notification, InputLayer::LinkedRename]);
+        assert_eq!(stack.key_context(base), base);
+        assert_eq!(FocusStack::new(vec![]).key_context(base), base);
+    }
+
+    #[test]
+    fn test_escape_closes_transient_surfaces_only() {
+        assert!(InputLayer::Prompt.closes_on_escape());
+        assert!(InputLayer::Completion.closes_on_escape());
+        assert!(!InputLayer::Settings.closes_on_escape());
+        assert!(!InputLayer::Hover.closes_on_escape());
+        assert!(!InputLayer::Mode("vi-insert".to_string()).closes_on_escape());
+    }
+}
@@ -2,7 +2,11 @@
 //!
 //! Provides substring-style fuzzy matching where query characters must appear
 //! in order in the target string, but not necessarily consecutively.
-//! Matching is case-insensitive.
+//! Matching is case-insensitive. A query with an uppercase letter (see
+//! [`CaseMatching::Smart`]) still matches in any case, but characters
+//! matched in the query's case score higher.
+
+use crate::primitives::case_matching::CaseMatching;
 
 /// Score bonus constants for match quality ranking
 mod score {
@@ -18,6 +22,9 @@ mod score {
     pub const GAP_PENALTY: i32 = -3;
     /// Penalty for starting a gap (first unmatched char after a match)
     pub const GAP_START_PENALTY: i32 = -5;
+    /// Bonus per character matched in the case typed, for queries with an
+    /// uppercase letter
+    pub const EXACT_CASE: i32 = 4;
 }
 
 /// Result of a fuzzy match, containing match status and quality score
@@ -100,10 +107,21 @@ pub fn fuzzy_match(query: &str, target: &str) -> FuzzyMatch {
     let query_lower: Vec<char> = query.to_lowercase().chars().collect();
     let target_chars: Vec<char> = target.chars().collect();
     let target_lower: Vec<char> = target.to_lowercase().chars().collect();
+    // The query as typed, when its case counts and lines up with the
+    // lowercased one
+    let query_case: Option<Vec<char>> = CaseMatching::Smart
+        .is_case_sensitive(query, false)
+        .then(|| query.chars().collect::<Vec<char>>())
+        .filter(|chars| chars.len() == query_lower.len());
 
     // Try to find the best matching positions using a greedy approach
     // that considers bonuses at each step
-    let result = find_best_match(&query_lower, &target_chars, &target_lower);
+    let result = find_best_match(
+        &query_lower,
+        query_case.as_deref(),
+        &target_chars,
+        &target_lower,
+    );
 
     if let Some((positions, score)) = result {
         FuzzyMatch {
@@ -116,9 +134,11 @@ pub fn fuzzy_match(query: &str, target: &str) -> FuzzyMatch {
     }
 }
 
-/// Find the best matching positions for query in target
+/// Find the best matching positions for query in target, favoring
+/// characters in the case of `query_case`
 fn find_best_match(
     query: &[char],
+    query_case: Option<&[char]>,
     target_chars: &[char],
     target_lower: &[char],
 ) -> Option<(Vec<usize>, i32)> {
@@ -207,6 +227,11 @@ fn find_best_match(
                 }
             }
 
+            // Exact case bonus
+            if query_case.is_some_and(|typed| target_chars.get(ti) == Some(&typed[qi])) {
+                match_score += score::EXACT_CASE;
+            }
+
             // Consecutive match bonus
             if let Some(last_pos) = prev.last_match_pos {
                 if ti == last_pos + 1 {
@@ -292,6 +317,25 @@ mod tests {
         assert!(result.matched);
     }
 
+    #[test]
+    fn test_uppercase_query_prefers_matching_case() {
+        let exact = fuzzy_match("Save", "Save file");
+        let other = fuzzy_match("Save", "save file");
+        assert!(other.matched);
+        assert!(
+            exact.score > other.score,
+            "exact: {}, other: {}",
+            exact.score,
+            other.score
+        );
+
+        // Lowercase queries don't care about case
+        assert_eq!(
+            fuzzy_match("save", "Save file").score,
+            fuzzy_match("save", "save file").score
+        );
+    }
+
     #[test]
     fn test_substring_match() {
         let result = fuzzy_match("file", "Save File");
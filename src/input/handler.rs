@@ -97,6 +97,8 @@ pub enum DeferredAction {
     },
     /// Paste text from clipboard into the active settings input
     PasteToSettings,
+    /// Copy text cut or copied from a settings input to the clipboard
+    CopyFromSettings(String),
     /// Open the config file for the specified layer in the editor
     OpenConfigFile {
         layer: crate::config_io::ConfigLayer,
@@ -112,6 +114,8 @@ pub enum DeferredAction {
     // Prompt actions
     ClosePrompt,
     ConfirmPrompt,
+    /// Confirm the prompt, running the chosen command as a dry run
+    ConfirmPromptDryRun,
     UpdatePromptSuggestions,
     PromptHistoryPrev,
     PromptHistoryNext,
@@ -383,29 +383,6 @@ impl InputHistory {
     }
 }
 
-/// Get the data directory for Fresh editor state
-/// Returns $XDG_DATA_HOME/fresh or ~/.local/share/fresh on Linux
-/// Returns ~/Library/Application Support/fresh on macOS
-pub fn get_data_dir() -> std::io::Result<std::path::PathBuf> {
-    let data_dir = dirs::data_dir().ok_or_else(|| {
-        std::io::Error::new(
-            std::io::ErrorKind::NotFound,
-            "Could not determine data directory",
-        )
-    })?;
-    Ok(data_dir.join("fresh"))
-}
-
-/// Get the path for search history file
-pub fn get_search_history_path() -> std::io::Result<std::path::PathBuf> {
-    Ok(get_data_dir()?.join("search_history.json"))
-}
-
-/// Get the path for replace history file
-pub fn get_replace_history_path() -> std::io::Result<std::path::PathBuf> {
-    Ok(get_data_dir()?.join("replace_history.json"))
-}
-
 impl Default for InputHistory {
     fn default() -> Self {
         Self::new()
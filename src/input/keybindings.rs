@@ -62,6 +62,7 @@ pub fn format_keybinding(keycode: &KeyCode, modifiers: &KeyModifiers) -> String
         KeyCode::End => result.push_str("End"),
         KeyCode::PageUp => result.push_str("PgUp"),
         KeyCode::PageDown => result.push_str("PgDn"),
+        KeyCode::Menu => result.push_str("Menu"),
         KeyCode::Char(' ') => result.push_str("Space"),
         KeyCode::Char(c) => result.push_str(&c.to_uppercase().to_string()),
         KeyCode::F(n) => result.push_str(&format!("F{}", n)),
@@ -225,6 +226,10 @@ pub enum Action {
     MovePageDown,
     MoveDocumentStart,
     MoveDocumentEnd,
+    MoveParagraphForward,
+    MoveParagraphBackward,
+    MoveSentenceForward,
+    MoveSentenceBackward,
 
     // Selection movement (extends selection while moving)
     SelectLeft,
@@ -242,7 +247,17 @@ pub enum Action {
     SelectAll,
     SelectWord,
     SelectLine,
+    SelectParagraph,
+    SelectParagraphForward,
+    SelectParagraphBackward,
+    SelectSentenceForward,
+    SelectSentenceBackward,
+    SelectSentence,
     ExpandSelection,
+    ShrinkSelection,
+    SelectionStatistics,
+    ExpandSelectionToIndentation,
+    ExtendSelectionByWord,
 
     // Block/rectangular selection (column-wise)
     BlockSelectLeft,
@@ -258,6 +273,10 @@ pub enum Action {
     DeleteLine,
     DeleteToLineEnd,
     DeleteToLineStart,
+    DeleteParagraphForward,
+    DeleteParagraphBackward,
+    DeleteSentenceForward,
+    DeleteSentenceBackward,
     TransposeChars,
     OpenLine,
 
@@ -272,6 +291,11 @@ pub enum Action {
     CopyWithTheme(String),
     Cut,
     Paste,
+    PasteReindent,
+    PasteVerbatim,
+    PasteFromHistory,
+    CyclePaste,
+    RetrySystemClipboard,
 
     // Vi-style yank (copy without selection, then restore cursor)
     YankWordForward,
@@ -290,16 +314,46 @@ pub enum Action {
     SaveAs,
     Open,
     SwitchProject,
+    OpenRecentProject,
+    TogglePinProject,
+    RestoreSessionSnapshot,
+    SaveLayoutAs,
+    SwitchLayout(String),  // Empty name = open the layout picker
+    SwitchLayoutAt(usize), // 1-based position in the saved layouts
+    DeleteLayout,
     New,
     Close,
     CloseTab,
+    CloseSavedBuffers,
     Quit,
     Revert,
+    RevertBufferToSaved,
+    CompareBufferWith,
+    CompareNextHunk,
+    ComparePreviousHunk,
+    CompareCopyHunkToLeft,
+    CompareCopyHunkToRight,
+    CompareWithClipboard,
+    CompareDirectories,
+    DirCompareOpen,
+    DirCompareToggleIdentical,
+    DirCompareRefresh,
     ToggleAutoRevert,
+    ToggleBufferAutoRevert,
+    ToggleFollowTail,
+    TogglePinBuffer,
+    EditAnyway,
+    UnlockProtectedFile,
     FormatBuffer,
+    RunSaveActions,
 
     // Navigation
     GotoLine,
+    GotoByteOffset,
+    GotoPercentage,
+    CopyDataPath,
+    GotoDataPath,
+    SelectDataValue,
     GoToMatchingBracket,
     JumpToNextError,
     JumpToPreviousError,
@@ -343,16 +397,43 @@ pub enum Action {
     // View
     ScrollUp,
     ScrollDown,
+    ScrollUpKeepCursor,
+    ScrollDownKeepCursor,
+    ScrollHalfPageUp,
+    ScrollHalfPageDown,
+    CursorLineToTop,
+    CursorLineToCenter,
+    CursorLineToBottom,
     ShowHelp,
     ShowKeyboardShortcuts,
+    KeyboardShortcutsFilter, // Filter the keyboard shortcuts view by key or action
     ShowWarnings,
     ShowLspStatus,
+    UpdateNow,
+    ShowSessionOwnership,
+    ReviewBlockedCommands,
+    CancelProgress,
     ClearWarnings,
+    RetryPluginInit,
+    FocusNotifications,
+    ShowNotificationHistory,
+    ShowUsageStats,
+    ExportUsageStats,
+    ExportSettingsArchive,
+    ImportSettingsArchive,
+    ShowFullMessage,
+    OpenEditorLog,
+    FilterEditorLog,
+    OpenEditorLogAtEntry,
     CommandPalette,
     ToggleLineWrap,
     ToggleComposeMode,
+    ToggleDistractionFree,
     SetComposeWidth,
     SelectTheme,
+    ReloadTheme,
+    ToggleLightDarkTheme,
+    CheckThemeContrast,
     SelectKeybindingMap,
     SelectCursorStyle,
     SelectLocale,
@@ -380,9 +461,11 @@ pub enum Action {
     IncreaseSplitSize,
     DecreaseSplitSize,
     ToggleMaximizeSplit,
+    TogglePaneLock,
 
     // Prompt mode actions
     PromptConfirm,
+    PromptConfirmDryRun, // Run the chosen command as a dry run
     PromptCancel,
     PromptBackspace,
     PromptDelete,
@@ -444,24 +527,41 @@ pub enum Action {
     FileExplorerRename,
     FileExplorerToggleHidden,
     FileExplorerToggleGitignored,
+    FileExplorerToggleDetails,
+    FileExplorerCycleSort,
+    FileExplorerShowAll,
+    FileExplorerContextMenu,
 
     // LSP operations
     LspCompletion,
     LspGotoDefinition,
     LspReferences,
     LspRename,
+    RenameInBuffer,
     LspHover,
     LspSignatureHelp,
     LspCodeActions,
+    BatchCodeAction,
     LspRestart,
     LspStop,
     ToggleInlayHints,
     ToggleMouseHover,
 
+    // Git
+    ToggleInlineBlame,
+    ToggleInlineBlameAll,
+    ShowBlameCommit,
+
     // View toggles
     ToggleLineNumbers,
     ToggleMouseCapture,
     ToggleDebugHighlights, // Debug mode: show highlight/overlay byte ranges
+    ToggleInputLatencyHud, // Diagnostics: show keystroke/frame latency percentiles
+    ShowInputLayers,       // Diagnostics: show the input layers and which took the last key
+    FreeCaches,            // Drop highlight caches and retained views
+    CompactBuffer,         // Drop undo history and compact the active buffer's memory
+    CompactAllBuffers,     // The same for every open buffer
+    BufferMemoryReport,    // Diagnostics: show the memory each buffer takes
     SetBackground,
     SetBackgroundBlend,
 
@@ -470,6 +570,8 @@ pub enum Action {
     SetLineEnding,
     ToggleIndentationStyle,
     ToggleTabIndicators,
+    ToggleRenderWhitespace,
+    ToggleAnsiColors,
     ResetBufferSettings,
 
     // Config operations
@@ -482,6 +584,7 @@ pub enum Action {
     FindPrevious,
     FindSelectionNext,     // Quick find next occurrence of selection (Ctrl+F3)
     FindSelectionPrevious, // Quick find previous occurrence of selection (Ctrl+Shift+F3)
+    SearchInAllSplits,     // Give every split the focused split's search
     Replace,
     QueryReplace, // Interactive replace (y/n/!/q for each match)
 
@@ -500,6 +603,7 @@ pub enum Action {
 
     // Plugin custom actions
     PluginAction(String),
+    RunCommand(String, serde_json::Map<String, serde_json::Value>), // Run a command by name with the values of its parameters
 
     // Settings operations
     OpenSettings,        // Open the settings modal
@@ -524,10 +628,46 @@ pub enum Action {
     // Shell command operations
     ShellCommand,        // Run shell command on buffer/selection, output to new buffer
     ShellCommandReplace, // Run shell command on buffer/selection, replace content
+    InsertFile,          // Insert a file's contents at the cursors
+    InsertCommandOutput, // Insert a shell command's output at the cursors
+    ToggleCommandStdin,  // Toggle sending selections to the Insert Command Output command
 
     // Case conversion
     ToUpperCase, // Convert selection to uppercase
     ToLowerCase, // Convert selection to lowercase
+    ToTitleCase, // Convert selection (or buffer) to Title Case
+    ToCamelCase, // Convert identifiers in selection (or buffer) to camelCase
+    ToSnakeCase, // Convert identifiers in selection (or buffer) to snake_case
+
+    // Line transforms, on the selected lines (or buffer)
+    SortLines,           // Sort, with options asked for in a prompt
+    UniqueLines,         // Remove repeated lines
+    UniqueAdjacentLines, // Collapse runs of the same line
+    ReverseLines,        // Reverse line order
+    ShuffleLines,        // Put lines in random order
+
+    // Column edits
+    PasteAsColumn,   // Paste the clipboard's lines one per cursor (or down a column)
+    AlignSelections, // Line up a character on the selected lines, asked for in a prompt
+
+    // Pending edits review
+    PendingEditsToggle,       // Toggle the edit (or file) under the cursor
+    PendingEditsToggleFile,   // Include/exclude every edit in the file under the cursor
+    PendingEditsToggleExpand, // Expand/collapse context around the edit under the cursor
+    PendingEditsApply,        // Apply the checked edits
+    PendingEditsCancel,       // Close the review without applying
+
+    // TODO comments
+    TodosList,    // Open the project TODO list and scan
+    TodosRefresh, // Scan the project for TODO comments again
+    TodosOpen,    // Jump to the TODO comment under the cursor
+
+    // Workspace diagnostics
+    WorkspaceDiagnostics,     // Open the workspace diagnostics panel
+    WorkspaceDiagnosticsOpen, // Expand the file or jump to the diagnostic under the cursor
+
+    // Remote files
+    RemoteDirOpen, // Open the entry under the cursor in a remote directory listing
 
     // No-op
     None,
@@ -559,6 +699,10 @@ impl Action {
             "move_page_down" => Some(Action::MovePageDown),
             "move_document_start" => Some(Action::MoveDocumentStart),
             "move_document_end" => Some(Action::MoveDocumentEnd),
+            "move_paragraph_forward" => Some(Action::MoveParagraphForward),
+            "move_paragraph_backward" => Some(Action::MoveParagraphBackward),
+            "move_sentence_forward" => Some(Action::MoveSentenceForward),
+            "move_sentence_backward" => Some(Action::MoveSentenceBackward),
 
             "select_left" => Some(Action::SelectLeft),
             "select_right" => Some(Action::SelectRight),
@@ -575,7 +719,17 @@ impl Action {
             "select_all" => Some(Action::SelectAll),
             "select_word" => Some(Action::SelectWord),
             "select_line" => Some(Action::SelectLine),
+            "select_paragraph" => Some(Action::SelectParagraph),
+            "select_paragraph_forward" => Some(Action::SelectParagraphForward),
+            "select_paragraph_backward" => Some(Action::SelectParagraphBackward),
+            "select_sentence_forward" => Some(Action::SelectSentenceForward),
+            "select_sentence_backward" => Some(Action::SelectSentenceBackward),
+            "select_sentence" => Some(Action::SelectSentence),
             "expand_selection" => Some(Action::ExpandSelection),
+            "shrink_selection" => Some(Action::ShrinkSelection),
+            "selection_statistics" => Some(Action::SelectionStatistics),
+            "expand_selection_to_indentation" => Some(Action::ExpandSelectionToIndentation),
+            "extend_selection_by_word" => Some(Action::ExtendSelectionByWord),
 
             // Block/rectangular selection
             "block_select_left" => Some(Action::BlockSelectLeft),
@@ -590,6 +744,10 @@ impl Action {
             "delete_line" => Some(Action::DeleteLine),
             "delete_to_line_end" => Some(Action::DeleteToLineEnd),
             "delete_to_line_start" => Some(Action::DeleteToLineStart),
+            "delete_paragraph_forward" => Some(Action::DeleteParagraphForward),
+            "delete_paragraph_backward" => Some(Action::DeleteParagraphBackward),
+            "delete_sentence_forward" => Some(Action::DeleteSentenceForward),
+            "delete_sentence_backward" => Some(Action::DeleteSentenceBackward),
             "transpose_chars" => Some(Action::TransposeChars),
             "open_line" => Some(Action::OpenLine),
             "recenter" => Some(Action::Recenter),
@@ -603,6 +761,11 @@ impl Action {
             }
             "cut" => Some(Action::Cut),
             "paste" => Some(Action::Paste),
+            "paste_reindent" => Some(Action::PasteReindent),
+            "paste_verbatim" => Some(Action::PasteVerbatim),
+            "paste_from_history" => Some(Action::PasteFromHistory),
+            "retry_system_clipboard" => Some(Action::RetrySystemClipboard),
+            "cycle_paste" => Some(Action::CyclePaste),
 
             // Vi-style yank actions
             "yank_word_forward" => Some(Action::YankWordForward),
@@ -619,14 +782,49 @@ impl Action {
             "save_as" => Some(Action::SaveAs),
             "open" => Some(Action::Open),
             "switch_project" => Some(Action::SwitchProject),
+            "open_recent_project" => Some(Action::OpenRecentProject),
+            "toggle_pin_project" => Some(Action::TogglePinProject),
+            "restore_session_snapshot" => Some(Action::RestoreSessionSnapshot),
+            "save_layout_as" => Some(Action::SaveLayoutAs),
+            "switch_layout" => {
+                if let Some(index) = args.get("index").and_then(|v| v.as_u64()) {
+                    return Some(Action::SwitchLayoutAt(index as usize));
+                }
+                let name = args.get("name").and_then(|v| v.as_str()).unwrap_or("");
+                Some(Action::SwitchLayout(name.to_string()))
+            }
+            "delete_layout" => Some(Action::DeleteLayout),
             "new" => Some(Action::New),
             "close" => Some(Action::Close),
             "close_tab" => Some(Action::CloseTab),
+            "close_saved_buffers" => Some(Action::CloseSavedBuffers),
             "quit" => Some(Action::Quit),
             "revert" => Some(Action::Revert),
+            "revert_buffer_to_saved" => Some(Action::RevertBufferToSaved),
+            "compare_buffer_with" => Some(Action::CompareBufferWith),
+            "compare_next_hunk" => Some(Action::CompareNextHunk),
+            "compare_previous_hunk" => Some(Action::ComparePreviousHunk),
+            "compare_copy_hunk_to_left" => Some(Action::CompareCopyHunkToLeft),
+            "compare_copy_hunk_to_right" => Some(Action::CompareCopyHunkToRight),
+            "compare_with_clipboard" => Some(Action::CompareWithClipboard),
+            "compare_directories" => Some(Action::CompareDirectories),
+            "dir_compare_open" => Some(Action::DirCompareOpen),
+            "dir_compare_toggle_identical" => Some(Action::DirCompareToggleIdentical),
+            "dir_compare_refresh" => Some(Action::DirCompareRefresh),
             "toggle_auto_revert" => Some(Action::ToggleAutoRevert),
+            "toggle_buffer_auto_revert" => Some(Action::ToggleBufferAutoRevert),
+            "toggle_follow_tail" => Some(Action::ToggleFollowTail),
+            "toggle_pin_buffer" => Some(Action::TogglePinBuffer),
+            "edit_anyway" => Some(Action::EditAnyway),
+            "unlock_protected_file" => Some(Action::UnlockProtectedFile),
             "format_buffer" => Some(Action::FormatBuffer),
+            "run_save_actions" => Some(Action::RunSaveActions),
             "goto_line" => Some(Action::GotoLine),
+            "goto_byte_offset" => Some(Action::GotoByteOffset),
+            "goto_percentage" => Some(Action::GotoPercentage),
+            "copy_data_path" => Some(Action::CopyDataPath),
+            "goto_data_path" => Some(Action::GotoDataPath),
+            "select_data_value" => Some(Action::SelectDataValue),
             "goto_matching_bracket" => Some(Action::GoToMatchingBracket),
             "jump_to_next_error" => Some(Action::JumpToNextError),
             "jump_to_previous_error" => Some(Action::JumpToPreviousError),
@@ -698,18 +896,43 @@ impl Action {
 
             "scroll_up" => Some(Action::ScrollUp),
             "scroll_down" => Some(Action::ScrollDown),
+            "scroll_up_keep_cursor" => Some(Action::ScrollUpKeepCursor),
+            "scroll_down_keep_cursor" => Some(Action::ScrollDownKeepCursor),
+            "scroll_half_page_up" => Some(Action::ScrollHalfPageUp),
+            "scroll_half_page_down" => Some(Action::ScrollHalfPageDown),
+            "cursor_line_to_top" => Some(Action::CursorLineToTop),
+            "cursor_line_to_center" => Some(Action::CursorLineToCenter),
+            "cursor_line_to_bottom" => Some(Action::CursorLineToBottom),
             "show_help" => Some(Action::ShowHelp),
             "keyboard_shortcuts" => Some(Action::ShowKeyboardShortcuts),
+            "keyboard_shortcuts_filter" => Some(Action::KeyboardShortcutsFilter),
             "show_warnings" => Some(Action::ShowWarnings),
+            "review_blocked_commands" => Some(Action::ReviewBlockedCommands),
+            "cancel_progress" => Some(Action::CancelProgress),
             "show_lsp_status" => Some(Action::ShowLspStatus),
+            "update_now" => Some(Action::UpdateNow),
+            "show_session_ownership" => Some(Action::ShowSessionOwnership),
             "clear_warnings" => Some(Action::ClearWarnings),
+            "retry_plugin_init" => Some(Action::RetryPluginInit),
+            "focus_notifications" => Some(Action::FocusNotifications),
+            "show_notification_history" => Some(Action::ShowNotificationHistory),
+            "show_usage_stats" => Some(Action::ShowUsageStats),
+            "export_usage_stats" => Some(Action::ExportUsageStats),
+            "export_settings_archive" => Some(Action::ExportSettingsArchive),
+            "import_settings_archive" => Some(Action::ImportSettingsArchive),
+            "show_full_message" => Some(Action::ShowFullMessage),
+            "open_editor_log" => Some(Action::OpenEditorLog),
+            "filter_editor_log" => Some(Action::FilterEditorLog),
+            "open_editor_log_at_entry" => Some(Action::OpenEditorLogAtEntry),
             "command_palette" => Some(Action::CommandPalette),
             "toggle_line_wrap" => Some(Action::ToggleLineWrap),
             "toggle_compose_mode" => Some(Action::ToggleComposeMode),
+            "toggle_distraction_free" => Some(Action::ToggleDistractionFree),
             "set_compose_width" => Some(Action::SetComposeWidth),
 
             "next_buffer" => Some(Action::NextBuffer),
             "prev_buffer" => Some(Action::PrevBuffer),
+            "switch_to_tab_by_name" => Some(Action::SwitchToTabByName),
 
             "navigate_back" => Some(Action::NavigateBack),
             "navigate_forward" => Some(Action::NavigateForward),
@@ -722,8 +945,10 @@ impl Action {
             "increase_split_size" => Some(Action::IncreaseSplitSize),
             "decrease_split_size" => Some(Action::DecreaseSplitSize),
             "toggle_maximize_split" => Some(Action::ToggleMaximizeSplit),
+            "toggle_pane_lock" => Some(Action::TogglePaneLock),
 
             "prompt_confirm" => Some(Action::PromptConfirm),
+            "prompt_confirm_dry_run" => Some(Action::PromptConfirmDryRun),
             "prompt_cancel" => Some(Action::PromptCancel),
             "prompt_backspace" => Some(Action::PromptBackspace),
             "prompt_move_left" => Some(Action::PromptMoveLeft),
@@ -778,25 +1003,43 @@ impl Action {
             "file_explorer_rename" => Some(Action::FileExplorerRename),
             "file_explorer_toggle_hidden" => Some(Action::FileExplorerToggleHidden),
             "file_explorer_toggle_gitignored" => Some(Action::FileExplorerToggleGitignored),
+            "file_explorer_toggle_details" => Some(Action::FileExplorerToggleDetails),
+            "file_explorer_cycle_sort" => Some(Action::FileExplorerCycleSort),
+            "file_explorer_show_all" => Some(Action::FileExplorerShowAll),
+            "file_explorer_context_menu" => Some(Action::FileExplorerContextMenu),
 
             "lsp_completion" => Some(Action::LspCompletion),
             "lsp_goto_definition" => Some(Action::LspGotoDefinition),
             "lsp_references" => Some(Action::LspReferences),
             "lsp_rename" => Some(Action::LspRename),
+            "rename_in_buffer" => Some(Action::RenameInBuffer),
             "lsp_hover" => Some(Action::LspHover),
             "lsp_signature_help" => Some(Action::LspSignatureHelp),
             "lsp_code_actions" => Some(Action::LspCodeActions),
+            "batch_code_action" => Some(Action::BatchCodeAction),
             "lsp_restart" => Some(Action::LspRestart),
             "lsp_stop" => Some(Action::LspStop),
             "toggle_inlay_hints" => Some(Action::ToggleInlayHints),
             "toggle_mouse_hover" => Some(Action::ToggleMouseHover),
+            "toggle_inline_blame" => Some(Action::ToggleInlineBlame),
+            "toggle_inline_blame_all" => Some(Action::ToggleInlineBlameAll),
+            "show_blame_commit" => Some(Action::ShowBlameCommit),
 
             "toggle_line_numbers" => Some(Action::ToggleLineNumbers),
             "toggle_mouse_capture" => Some(Action::ToggleMouseCapture),
             "toggle_debug_highlights" => Some(Action::ToggleDebugHighlights),
+            "toggle_input_latency_hud" => Some(Action::ToggleInputLatencyHud),
+            "show_input_layers" => Some(Action::ShowInputLayers),
+            "free_caches" => Some(Action::FreeCaches),
+            "compact_buffer" => Some(Action::CompactBuffer),
+            "compact_all_buffers" => Some(Action::CompactAllBuffers),
+            "buffer_memory_report" => Some(Action::BufferMemoryReport),
             "set_background" => Some(Action::SetBackground),
             "set_background_blend" => Some(Action::SetBackgroundBlend),
             "select_theme" => Some(Action::SelectTheme),
+            "reload_theme" => Some(Action::ReloadTheme),
+            "toggle_light_dark_theme" => Some(Action::ToggleLightDarkTheme),
+            "check_theme_contrast" => Some(Action::CheckThemeContrast),
             "select_keybinding_map" => Some(Action::SelectKeybindingMap),
             "select_locale" => Some(Action::SelectLocale),
 
@@ -805,6 +1048,8 @@ impl Action {
             "set_line_ending" => Some(Action::SetLineEnding),
             "toggle_indentation_style" => Some(Action::ToggleIndentationStyle),
             "toggle_tab_indicators" => Some(Action::ToggleTabIndicators),
+            "toggle_render_whitespace" => Some(Action::ToggleRenderWhitespace),
+            "toggle_ansi_colors" => Some(Action::ToggleAnsiColors),
             "reset_buffer_settings" => Some(Action::ResetBufferSettings),
 
             "dump_config" => Some(Action::DumpConfig),
@@ -815,6 +1060,7 @@ impl Action {
             "find_previous" => Some(Action::FindPrevious),
             "find_selection_next" => Some(Action::FindSelectionNext),
             "find_selection_previous" => Some(Action::FindSelectionPrevious),
+            "search_in_all_splits" => Some(Action::SearchInAllSplits),
             "replace" => Some(Action::Replace),
             "query_replace" => Some(Action::QueryReplace),
 
@@ -835,6 +1081,15 @@ impl Action {
                 Some(Action::SwitchKeybindingMap(map_name.to_string()))
             }
 
+            "run_command" => {
+                let name = args.get("command")?.as_str()?;
+                let values = match args.get("args") {
+                    Some(values) => values.as_object()?.clone(),
+                    None => serde_json::Map::new(),
+                };
+                Some(Action::RunCommand(name.to_string(), values))
+            }
+
             // Terminal actions
             "open_terminal" => Some(Action::OpenTerminal),
             "close_terminal" => Some(Action::CloseTerminal),
@@ -846,10 +1101,44 @@ impl Action {
             // Shell command actions
             "shell_command" => Some(Action::ShellCommand),
             "shell_command_replace" => Some(Action::ShellCommandReplace),
+            "insert_file" => Some(Action::InsertFile),
+            "insert_command_output" => Some(Action::InsertCommandOutput),
+            "toggle_command_stdin" => Some(Action::ToggleCommandStdin),
 
             // Case conversion
             "to_upper_case" => Some(Action::ToUpperCase),
             "to_lower_case" => Some(Action::ToLowerCase),
+            "to_title_case" => Some(Action::ToTitleCase),
+            "to_camel_case" => Some(Action::ToCamelCase),
+            "to_snake_case" => Some(Action::ToSnakeCase),
+
+            // Line transforms
+            "sort_lines" => Some(Action::SortLines),
+            "unique_lines" => Some(Action::UniqueLines),
+            "unique_adjacent_lines" => Some(Action::UniqueAdjacentLines),
+            "reverse_lines" => Some(Action::ReverseLines),
+            "shuffle_lines" => Some(Action::ShuffleLines),
+            "paste_as_column" => Some(Action::PasteAsColumn),
+            "align_selections" => Some(Action::AlignSelections),
+
+            // Pending edits review
+            "pending_edits_toggle" => Some(Action::PendingEditsToggle),
+            "pending_edits_toggle_file" => Some(Action::PendingEditsToggleFile),
+            "pending_edits_toggle_expand" => Some(Action::PendingEditsToggleExpand),
+            "pending_edits_apply" => Some(Action::PendingEditsApply),
+            "pending_edits_cancel" => Some(Action::PendingEditsCancel),
+
+            // TODO comments
+            "todos_list" => Some(Action::TodosList),
+            "todos_refresh" => Some(Action::TodosRefresh),
+            "todos_open" => Some(Action::TodosOpen),
+
+            // Workspace diagnostics
+            "workspace_diagnostics" => Some(Action::WorkspaceDiagnostics),
+            "workspace_diagnostics_open" => Some(Action::WorkspaceDiagnosticsOpen),
+
+            // Remote files
+            "remote_dir_open" => Some(Action::RemoteDirOpen),
 
             // Settings actions
             "open_settings" => Some(Action::OpenSettings),
@@ -866,6 +1155,387 @@ impl Action {
             _ => None,
         }
     }
+
+    /// Whether the action changes the text of the active buffer (or starts
+    /// something that will, like a rename or a replace)
+    pub fn edits_buffer(&self) -> bool {
+        // No catch-all arm, so every new action has to be sorted here
+        match self {
+            Action::InsertChar(_)
+            | Action::InsertNewline
+            | Action::InsertTab
+            | Action::DeleteBackward
+            | Action::DeleteForward
+            | Action::DeleteWordBackward
+            | Action::DeleteWordForward
+            | Action::DeleteLine
+            | Action::DeleteToLineEnd
+            | Action::DeleteToLineStart
+            | Action::DeleteParagraphForward
+            | Action::DeleteParagraphBackward
+            | Action::DeleteSentenceForward
+            | Action::DeleteSentenceBackward
+            | Action::TransposeChars
+            | Action::OpenLine
+            | Action::Cut
+            | Action::Paste
+            | Action::PasteReindent
+            | Action::PasteVerbatim
+            | Action::PasteFromHistory
+            | Action::CyclePaste
+            | Action::Revert
+            | Action::RevertBufferToSaved
+            | Action::CompareCopyHunkToLeft
+            | Action::CompareCopyHunkToRight
+            | Action::FormatBuffer
+            | Action::RunSaveActions
+            | Action::DedentSelection
+            | Action::ToggleComment
+            | Action::Undo
+            | Action::Redo
+            | Action::LspCompletion
+            | Action::LspRename
+            | Action::RenameInBuffer
+            | Action::LspCodeActions
+            | Action::Replace
+            | Action::QueryReplace
+            | Action::ShellCommandReplace
+            | Action::InsertFile
+            | Action::InsertCommandOutput
+            | Action::ToUpperCase
+            | Action::ToLowerCase
+            | Action::ToTitleCase
+            | Action::ToCamelCase
+            | Action::ToSnakeCase
+            | Action::SortLines
+            | Action::UniqueLines
+            | Action::UniqueAdjacentLines
+            | Action::ReverseLines
+            | Action::ShuffleLines
+            | Action::PasteAsColumn
+            | Action::AlignSelections => true,
+            Action::MoveLeft
+            | Action::MoveRight
+            | Action::MoveUp
+            | Action::MoveDown
+            | Action::MoveWordLeft
+            | Action::MoveWordRight
+            | Action::MoveLineStart
+            | Action::MoveLineEnd
+            | Action::MovePageUp
+            | Action::MovePageDown
+            | Action::MoveDocumentStart
+            | Action::MoveDocumentEnd
+            | Action::MoveParagraphForward
+            | Action::MoveParagraphBackward
+            | Action::MoveSentenceForward
+            | Action::MoveSentenceBackward
+            | Action::SelectLeft
+            | Action::SelectRight
+            | Action::SelectUp
+            | Action::SelectDown
+            | Action::SelectWordLeft
+            | Action::SelectWordRight
+            | Action::SelectLineStart
+            | Action::SelectLineEnd
+            | Action::SelectDocumentStart
+            | Action::SelectDocumentEnd
+            | Action::SelectPageUp
+            | Action::SelectPageDown
+            | Action::SelectAll
+            | Action::SelectWord
+            | Action::SelectLine
+            | Action::SelectParagraph
+            | Action::SelectParagraphForward
+            | Action::SelectParagraphBackward
+            | Action::SelectSentenceForward
+            | Action::SelectSentenceBackward
+            | Action::SelectSentence
+            | Action::ExpandSelection
+            | Action::ShrinkSelection
+            | Action::SelectionStatistics
+            | Action::ExpandSelectionToIndentation
+            | Action::ExtendSelectionByWord
+            | Action::BlockSelectLeft
+            | Action::BlockSelectRight
+            | Action::BlockSelectUp
+            | Action::BlockSelectDown
+            | Action::Recenter
+            | Action::SetMark
+            | Action::Copy
+            | Action::CopyWithTheme(_)
+            | Action::RetrySystemClipboard
+            | Action::YankWordForward
+            | Action::YankWordBackward
+            | Action::YankToLineEnd
+            | Action::YankToLineStart
+            | Action::AddCursorAbove
+            | Action::AddCursorBelow
+            | Action::AddCursorNextMatch
+            | Action::RemoveSecondaryCursors
+            | Action::Save
+            | Action::SaveAs
+            | Action::Open
+            | Action::SwitchProject
+            | Action::OpenRecentProject
+            | Action::TogglePinProject
+            | Action::RestoreSessionSnapshot
+            | Action::SaveLayoutAs
+            | Action::SwitchLayout(_)
+            | Action::SwitchLayoutAt(_)
+            | Action::DeleteLayout
+            | Action::New
+            | Action::Close
+            | Action::CloseTab
+            | Action::CloseSavedBuffers
+            | Action::Quit
+            | Action::CompareBufferWith
+            | Action::CompareNextHunk
+            | Action::ComparePreviousHunk
+            | Action::CompareWithClipboard
+            | Action::CompareDirectories
+            | Action::DirCompareOpen
+            | Action::DirCompareToggleIdentical
+            | Action::DirCompareRefresh
+            | Action::ToggleAutoRevert
+            | Action::ToggleBufferAutoRevert
+            | Action::ToggleFollowTail
+            | Action::TogglePinBuffer
+            | Action::EditAnyway
+            | Action::UnlockProtectedFile
+            | Action::GotoLine
+            | Action::GotoByteOffset
+            | Action::GotoPercentage
+            | Action::CopyDataPath
+            | Action::GotoDataPath
+            | Action::SelectDataValue
+            | Action::GoToMatchingBracket
+            | Action::JumpToNextError
+            | Action::JumpToPreviousError
+            | Action::SmartHome
+            | Action::SetBookmark(_)
+            | Action::JumpToBookmark(_)
+            | Action::ClearBookmark(_)
+            | Action::ListBookmarks
+            | Action::ToggleSearchCaseSensitive
+            | Action::ToggleSearchWholeWord
+            | Action::ToggleSearchRegex
+            | Action::ToggleSearchConfirmEach
+            | Action::StartMacroRecording
+            | Action::StopMacroRecording
+            | Action::PlayMacro(_)
+            | Action::ToggleMacroRecording(_)
+            | Action::ShowMacro(_)
+            | Action::ListMacros
+            | Action::PromptRecordMacro
+            | Action::PromptPlayMacro
+            | Action::PlayLastMacro
+            | Action::PromptSetBookmark
+            | Action::PromptJumpToBookmark
+            | Action::ScrollUp
+            | Action::ScrollDown
+            | Action::ScrollUpKeepCursor
+            | Action::ScrollDownKeepCursor
+            | Action::ScrollHalfPageUp
+            | Action::ScrollHalfPageDown
+            | Action::CursorLineToTop
+            | Action::CursorLineToCenter
+            | Action::CursorLineToBottom
+            | Action::ShowHelp
+            | Action::ShowKeyboardShortcuts
+            | Action::KeyboardShortcutsFilter
+            | Action::ShowWarnings
+            | Action::ShowLspStatus
+            | Action::UpdateNow
+            | Action::ShowSessionOwnership
+            | Action::ReviewBlockedCommands
+            | Action::CancelProgress
+            | Action::ClearWarnings
+            | Action::RetryPluginInit
+            | Action::FocusNotifications
+            | Action::ShowNotificationHistory
+            | Action::ShowUsageStats
+            | Action::ExportUsageStats
+            | Action::ExportSettingsArchive
+            | Action::ImportSettingsArchive
+            | Action::ShowFullMessage
+            | Action::OpenEditorLog
+            | Action::FilterEditorLog
+            | Action::OpenEditorLogAtEntry
+            | Action::CommandPalette
+            | Action::ToggleLineWrap
+            | Action::ToggleComposeMode
+            | Action::ToggleDistractionFree
+            | Action::SetComposeWidth
+            | Action::SelectTheme
+            | Action::ReloadTheme
+            | Action::ToggleLightDarkTheme
+            | Action::CheckThemeContrast
+            | Action::SelectKeybindingMap
+            | Action::SelectCursorStyle
+            | Action::SelectLocale
+            | Action::NextBuffer
+            | Action::PrevBuffer
+            | Action::SwitchToPreviousTab
+            | Action::SwitchToTabByName
+            | Action::ScrollTabsLeft
+            | Action::ScrollTabsRight
+            | Action::NavigateBack
+            | Action::NavigateForward
+            | Action::SplitHorizontal
+            | Action::SplitVertical
+            | Action::CloseSplit
+            | Action::NextSplit
+            | Action::PrevSplit
+            | Action::IncreaseSplitSize
+            | Action::DecreaseSplitSize
+            | Action::ToggleMaximizeSplit
+            | Action::TogglePaneLock
+            | Action::PromptConfirm
+            | Action::PromptConfirmDryRun
+            | Action::PromptCancel
+            | Action::PromptBackspace
+            | Action::PromptDelete
+            | Action::PromptMoveLeft
+            | Action::PromptMoveRight
+            | Action::PromptMoveStart
+            | Action::PromptMoveEnd
+            | Action::PromptSelectPrev
+            | Action::PromptSelectNext
+            | Action::PromptPageUp
+            | Action::PromptPageDown
+            | Action::PromptAcceptSuggestion
+            | Action::PromptMoveWordLeft
+            | Action::PromptMoveWordRight
+            | Action::PromptDeleteWordForward
+            | Action::PromptDeleteWordBackward
+            | Action::PromptDeleteToLineEnd
+            | Action::PromptCopy
+            | Action::PromptCut
+            | Action::PromptPaste
+            | Action::PromptMoveLeftSelecting
+            | Action::PromptMoveRightSelecting
+            | Action::PromptMoveHomeSelecting
+            | Action::PromptMoveEndSelecting
+            | Action::PromptSelectWordLeft
+            | Action::PromptSelectWordRight
+            | Action::PromptSelectAll
+            | Action::FileBrowserToggleHidden
+            | Action::PopupSelectNext
+            | Action::PopupSelectPrev
+            | Action::PopupPageUp
+            | Action::PopupPageDown
+            | Action::PopupConfirm
+            | Action::PopupCancel
+            | Action::ToggleFileExplorer
+            | Action::ToggleMenuBar
+            | Action::FocusFileExplorer
+            | Action::FocusEditor
+            | Action::FileExplorerUp
+            | Action::FileExplorerDown
+            | Action::FileExplorerPageUp
+            | Action::FileExplorerPageDown
+            | Action::FileExplorerExpand
+            | Action::FileExplorerCollapse
+            | Action::FileExplorerOpen
+            | Action::FileExplorerRefresh
+            | Action::FileExplorerNewFile
+            | Action::FileExplorerNewDirectory
+            | Action::FileExplorerDelete
+            | Action::FileExplorerRename
+            | Action::FileExplorerToggleHidden
+            | Action::FileExplorerToggleGitignored
+            | Action::FileExplorerToggleDetails
+            | Action::FileExplorerCycleSort
+            | Action::FileExplorerShowAll
+            | Action::FileExplorerContextMenu
+            | Action::LspGotoDefinition
+            | Action::LspReferences
+            | Action::LspHover
+            | Action::LspSignatureHelp
+            | Action::BatchCodeAction
+            | Action::LspRestart
+            | Action::LspStop
+            | Action::ToggleInlayHints
+            | Action::ToggleMouseHover
+            | Action::ToggleInlineBlame
+            | Action::ToggleInlineBlameAll
+            | Action::ShowBlameCommit
+            | Action::ToggleLineNumbers
+            | Action::ToggleMouseCapture
+            | Action::ToggleDebugHighlights
+            | Action::ToggleInputLatencyHud
+            | Action::ShowInputLayers
+            | Action::FreeCaches
+            | Action::CompactBuffer
+            | Action::CompactAllBuffers
+            | Action::BufferMemoryReport
+            | Action::SetBackground
+            | Action::SetBackgroundBlend
+            | Action::SetTabSize
+            | Action::SetLineEnding
+            | Action::ToggleIndentationStyle
+            | Action::ToggleTabIndicators
+            | Action::ToggleRenderWhitespace
+            | Action::ToggleAnsiColors
+            | Action::ResetBufferSettings
+            | Action::DumpConfig
+            | Action::Search
+            | Action::FindInSelection
+            | Action::FindNext
+            | Action::FindPrevious
+            | Action::FindSelectionNext
+            | Action::FindSelectionPrevious
+            | Action::SearchInAllSplits
+            | Action::MenuActivate
+            | Action::MenuClose
+            | Action::MenuLeft
+            | Action::MenuRight
+            | Action::MenuUp
+            | Action::MenuDown
+            | Action::MenuExecute
+            | Action::MenuOpen(_)
+            | Action::SwitchKeybindingMap(_)
+            | Action::PluginAction(_)
+            | Action::RunCommand(..)
+            | Action::OpenSettings
+            | Action::CloseSettings
+            | Action::SettingsSave
+            | Action::SettingsReset
+            | Action::SettingsToggleFocus
+            | Action::SettingsActivate
+            | Action::SettingsSearch
+            | Action::SettingsHelp
+            | Action::SettingsIncrement
+            | Action::SettingsDecrement
+            | Action::OpenTerminal
+            | Action::CloseTerminal
+            | Action::FocusTerminal
+            | Action::TerminalEscape
+            | Action::ToggleKeyboardCapture
+            | Action::TerminalPaste
+            | Action::ShellCommand
+            | Action::ToggleCommandStdin
+            | Action::PendingEditsToggle
+            | Action::PendingEditsToggleFile
+            | Action::PendingEditsToggleExpand
+            | Action::PendingEditsApply
+            | Action::PendingEditsCancel
+            | Action::TodosList
+            | Action::TodosRefresh
+            | Action::TodosOpen
+            | Action::WorkspaceDiagnostics
+            | Action::WorkspaceDiagnosticsOpen
+            | Action::RemoteDirOpen
+            | Action::None => false,
+        }
+    }
+
+    /// Whether the built-in action reports what it would do instead of
+    /// doing it when run as a dry run (plugin actions declare it themselves)
+    pub fn supports_dry_run(&self) -> bool {
+        matches!(self, Action::CloseSavedBuffers | Action::PendingEditsApply)
+    }
 }
 
 /// Result of chord resolution
@@ -879,6 +1549,93 @@ pub enum ChordResolution {
     NoMatch,
 }
 
+/// Where a keybinding was defined
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BindingSource {
+    /// A keymap: the active one or one it inherits from
+    Keymap(String),
+    /// The `keybindings` list of the user config
+    User,
+    /// A buffer or editor mode, and the plugin that defined it if any
+    Mode {
+        mode: String,
+        plugin: Option<String>,
+    },
+}
+
+impl std::fmt::Display for BindingSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BindingSource::Keymap(name) => write!(f, "keymap {}", name),
+            BindingSource::User => write!(f, "user config"),
+            BindingSource::Mode {
+                mode,
+                plugin: Some(plugin),
+            } => write!(f, "plugin {} mode {}", plugin, mode),
+            BindingSource::Mode { mode, plugin: None } => write!(f, "mode {}", mode),
+        }
+    }
+}
+
+/// A binding that lost to the winning binding of the same keys
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShadowedBinding {
+    /// Context the binding was defined for
+    pub context: KeyContext,
+    /// Action or command name
+    pub action: String,
+    pub source: BindingSource,
+}
+
+/// One key (or chord) of the effective keymap, with its provenance
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeymapEntry {
+    /// Context the winning binding was defined for (Global, or a fallback
+    /// to Normal for application-wide actions)
+    pub context: KeyContext,
+    /// Key sequence; a single element unless this is a chord
+    pub keys: Vec<(KeyCode, KeyModifiers)>,
+    /// Action or command name, as written in the binding
+    pub action: String,
+    /// Human-readable description of the action
+    pub description: String,
+    pub source: BindingSource,
+    /// Bindings of the same keys that this one wins over, highest priority first
+    pub shadowed: Vec<ShadowedBinding>,
+}
+
+impl KeymapEntry {
+    /// Display form of the key sequence (e.g. "Ctrl+K Ctrl+C")
+    pub fn key_display(&self) -> String {
+        self.keys
+            .iter()
+            .map(
+                |(code, modifiers)| match format_keybinding(code, modifiers) {
+                    // Keys without a display name (BackTab, media keys, ...)
+                    key if key.is_empty() => format!("{:?}", code),
+                    key => key,
+                },
+            )
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// Whether a losing binding of the same keys does something else
+    pub fn is_conflict(&self) -> bool {
+        self.shadowed.iter().any(|s| s.action != self.action)
+    }
+}
+
+/// A binding as loaded from configuration, kept for `KeybindingResolver::keymap`
+#[derive(Debug, Clone)]
+struct BindingRecord {
+    context: KeyContext,
+    keys: Vec<(KeyCode, KeyModifiers)>,
+    action: Action,
+    action_name: String,
+    source: BindingSource,
+}
+
 /// Resolves key events to actions based on configuration
 #[derive(Clone)]
 pub struct KeybindingResolver {
@@ -895,6 +1652,9 @@ pub struct KeybindingResolver {
 
     /// Default chord bindings for each context
     default_chord_bindings: HashMap<KeyContext, HashMap<Vec<(KeyCode, KeyModifiers)>, Action>>,
+
+    /// Every loaded binding in load order, for introspection
+    records: Vec<BindingRecord>,
 }
 
 impl KeybindingResolver {
@@ -905,10 +1665,11 @@ impl KeybindingResolver {
             default_bindings: HashMap::new(),
             chord_bindings: HashMap::new(),
             default_chord_bindings: HashMap::new(),
+            records: Vec::new(),
         };
 
         // Load bindings from the active keymap (with inheritance resolution) into default_bindings
-        let map_bindings = config.resolve_keymap(&config.active_keybinding_map);
+        let map_bindings = config.resolve_keymap_with_sources(&config.active_keybinding_map);
         resolver.load_default_bindings_from_vec(&map_bindings);
 
         // Then, load custom keybindings (these override the default map bindings)
@@ -918,8 +1679,9 @@ impl KeybindingResolver {
     }
 
     /// Load default bindings from a vector of keybinding definitions (into default_bindings/default_chord_bindings)
-    fn load_default_bindings_from_vec(&mut self, bindings: &[crate::config::Keybinding]) {
-        for binding in bindings {
+    /// Each binding is paired with the name of the keymap defining it
+    fn load_default_bindings_from_vec(&mut self, bindings: &[(String, crate::config::Keybinding)]) {
+        for (map_name, binding) in bindings {
             // Determine context from "when" clause
             let context = if let Some(ref when) = binding.when {
                 KeyContext::from_when_clause(when).unwrap_or(KeyContext::Normal)
@@ -944,6 +1706,13 @@ impl KeybindingResolver {
 
                     // Only add if all keys in sequence were valid
                     if sequence.len() == binding.keys.len() && !sequence.is_empty() {
+                        self.record(
+                            context,
+                            sequence.clone(),
+                            &action,
+                            binding,
+                            BindingSource::Keymap(map_name.clone()),
+                        );
                         self.default_chord_bindings
                             .entry(context)
                             .or_insert_with(HashMap::new)
@@ -952,6 +1721,13 @@ impl KeybindingResolver {
                 } else if let Some(key_code) = Self::parse_key(&binding.key) {
                     // Single key binding (legacy format)
                     let modifiers = Self::parse_modifiers(&binding.modifiers);
+                    self.record(
+                        context,
+                        vec![(key_code, modifiers)],
+                        &action,
+                        binding,
+                        BindingSource::Keymap(map_name.clone()),
+                    );
 
                     // Insert the primary binding
                     self.insert_binding_with_equivalents(
@@ -1039,6 +1815,13 @@ impl KeybindingResolver {
 
                     // Only add if all keys in sequence were valid
                     if sequence.len() == binding.keys.len() && !sequence.is_empty() {
+                        self.record(
+                            context,
+                            sequence.clone(),
+                            &action,
+                            binding,
+                            BindingSource::User,
+                        );
                         self.chord_bindings
                             .entry(context)
                             .or_insert_with(HashMap::new)
@@ -1047,6 +1830,13 @@ impl KeybindingResolver {
                 } else if let Some(key_code) = Self::parse_key(&binding.key) {
                     // Single key binding (legacy format)
                     let modifiers = Self::parse_modifiers(&binding.modifiers);
+                    self.record(
+                        context,
+                        vec![(key_code, modifiers)],
+                        &action,
+                        binding,
+                        BindingSource::User,
+                    );
                     self.bindings
                         .entry(context)
                         .or_insert_with(HashMap::new)
@@ -1056,6 +1846,92 @@ impl KeybindingResolver {
         }
     }
 
+    /// Remember a loaded binding for `keymap`
+    fn record(
+        &mut self,
+        context: KeyContext,
+        keys: Vec<(KeyCode, KeyModifiers)>,
+        action: &Action,
+        binding: &crate::config::Keybinding,
+        source: BindingSource,
+    ) {
+        self.records.push(BindingRecord {
+            context,
+            keys,
+            action: action.clone(),
+            action_name: binding.action.clone(),
+            source,
+        });
+    }
+
+    /// The effective keymap for a context, with the provenance of every binding
+    ///
+    /// Follows the lookup order of `resolve` and `resolve_chord`: custom then
+    /// default Global bindings, custom then default bindings of the context,
+    /// and for single keys the application-wide actions of Normal. Within one
+    /// of those levels the binding loaded last wins, as it does in the maps.
+    /// Terminal key equivalents that are added automatically are not listed.
+    pub fn keymap(&self, context: KeyContext) -> Vec<KeymapEntry> {
+        let rank = |record: &BindingRecord| -> Option<usize> {
+            let is_default = usize::from(record.source != BindingSource::User);
+            if record.context == KeyContext::Global {
+                Some(is_default)
+            } else if record.context == context {
+                Some(2 + is_default)
+            } else if context != KeyContext::Normal
+                && record.context == KeyContext::Normal
+                && record.keys.len() == 1
+                && Self::is_application_wide_action(&record.action)
+            {
+                Some(4 + is_default)
+            } else {
+                None
+            }
+        };
+
+        let mut by_keys: HashMap<_, Vec<(usize, usize)>> = HashMap::new();
+        for (index, record) in self.records.iter().enumerate() {
+            if let Some(rank) = rank(record) {
+                by_keys
+                    .entry(record.keys.as_slice())
+                    .or_default()
+                    .push((rank, index));
+            }
+        }
+
+        let mut entries: Vec<KeymapEntry> = by_keys
+            .into_values()
+            .map(|mut candidates| {
+                candidates.sort_by_key(|&(rank, index)| (rank, std::cmp::Reverse(index)));
+                let winner = &self.records[candidates[0].1];
+                KeymapEntry {
+                    context: winner.context,
+                    keys: winner.keys.clone(),
+                    action: winner.action_name.clone(),
+                    description: Self::format_action(&winner.action),
+                    source: winner.source.clone(),
+                    shadowed: candidates[1..]
+                        .iter()
+                        .map(|&(_, index)| {
+                            let record = &self.records[index];
+                            ShadowedBinding {
+                                context: record.context,
+                                action: record.action_name.clone(),
+                                source: record.source.clone(),
+                            }
+                        })
+                        .collect(),
+                }
+            })
+            .collect();
+        entries.sort_by(|a, b| {
+            a.description
+                .cmp(&b.description)
+                .then_with(|| a.key_display().cmp(&b.key_display()))
+        });
+        entries
+    }
+
     /// Check if an action is application-wide (should be accessible in all contexts)
     fn is_application_wide_action(action: &Action) -> bool {
         matches!(
@@ -1081,6 +1957,8 @@ impl KeybindingResolver {
                 | Action::OpenSettings
                 | Action::MenuActivate
                 | Action::MenuOpen(_)
+                | Action::SwitchLayout(_)
+                | Action::SwitchLayoutAt(_)
                 | Action::ShowHelp
                 | Action::ShowKeyboardShortcuts
                 | Action::Quit
@@ -1453,6 +2331,7 @@ impl KeybindingResolver {
             KeyCode::End => "End".to_string(),
             KeyCode::PageUp => "PageUp".to_string(),
             KeyCode::PageDown => "PageDown".to_string(),
+            KeyCode::Menu => "Menu".to_string(),
             KeyCode::F(n) => format!("F{}", n),
             _ => return String::new(),
         };
@@ -1481,6 +2360,7 @@ impl KeybindingResolver {
             "end" => Some(KeyCode::End),
             "pageup" => Some(KeyCode::PageUp),
             "pagedown" => Some(KeyCode::PageDown),
+            "menu" => Some(KeyCode::Menu),
 
             s if s.len() == 1 => s.chars().next().map(KeyCode::Char),
             // Handle function keys like "f1", "f2", ..., "f12"
@@ -1560,7 +2440,7 @@ impl KeybindingResolver {
     }
 
     /// Format an action as a readable description
-    fn format_action(action: &Action) -> String {
+    pub(crate) fn format_action(action: &Action) -> String {
         match action {
             Action::InsertChar(c) => t!("action.insert_char", char = c).to_string(),
             Action::InsertNewline => t!("action.insert_newline").to_string(),
@@ -1577,6 +2457,10 @@ impl KeybindingResolver {
             Action::MovePageDown => t!("action.move_page_down").to_string(),
             Action::MoveDocumentStart => t!("action.move_document_start").to_string(),
             Action::MoveDocumentEnd => t!("action.move_document_end").to_string(),
+            Action::MoveParagraphForward => t!("action.move_paragraph_forward").to_string(),
+            Action::MoveParagraphBackward => t!("action.move_paragraph_backward").to_string(),
+            Action::MoveSentenceForward => t!("action.move_sentence_forward").to_string(),
+            Action::MoveSentenceBackward => t!("action.move_sentence_backward").to_string(),
             Action::SelectLeft => t!("action.select_left").to_string(),
             Action::SelectRight => t!("action.select_right").to_string(),
             Action::SelectUp => t!("action.select_up").to_string(),
@@ -1592,7 +2476,19 @@ impl KeybindingResolver {
             Action::SelectAll => t!("action.select_all").to_string(),
             Action::SelectWord => t!("action.select_word").to_string(),
             Action::SelectLine => t!("action.select_line").to_string(),
+            Action::SelectParagraph => t!("action.select_paragraph").to_string(),
+            Action::SelectParagraphForward => t!("action.select_paragraph_forward").to_string(),
+            Action::SelectParagraphBackward => t!("action.select_paragraph_backward").to_string(),
+            Action::SelectSentenceForward => t!("action.select_sentence_forward").to_string(),
+            Action::SelectSentenceBackward => t!("action.select_sentence_backward").to_string(),
+            Action::SelectSentence => t!("action.select_sentence").to_string(),
             Action::ExpandSelection => t!("action.expand_selection").to_string(),
+            Action::ShrinkSelection => t!("action.shrink_selection").to_string(),
+            Action::SelectionStatistics => t!("action.selection_statistics").to_string(),
+            Action::ExpandSelectionToIndentation => {
+                t!("action.expand_selection_to_indentation").to_string()
+            }
+            Action::ExtendSelectionByWord => t!("action.extend_selection_by_word").to_string(),
             Action::BlockSelectLeft => t!("action.block_select_left").to_string(),
             Action::BlockSelectRight => t!("action.block_select_right").to_string(),
             Action::BlockSelectUp => t!("action.block_select_up").to_string(),
@@ -1604,6 +2500,10 @@ impl KeybindingResolver {
             Action::DeleteLine => t!("action.delete_line").to_string(),
             Action::DeleteToLineEnd => t!("action.delete_to_line_end").to_string(),
             Action::DeleteToLineStart => t!("action.delete_to_line_start").to_string(),
+            Action::DeleteParagraphForward => t!("action.delete_paragraph_forward").to_string(),
+            Action::DeleteParagraphBackward => t!("action.delete_paragraph_backward").to_string(),
+            Action::DeleteSentenceForward => t!("action.delete_sentence_forward").to_string(),
+            Action::DeleteSentenceBackward => t!("action.delete_sentence_backward").to_string(),
             Action::TransposeChars => t!("action.transpose_chars").to_string(),
             Action::OpenLine => t!("action.open_line").to_string(),
             Action::Recenter => t!("action.recenter").to_string(),
@@ -1615,6 +2515,11 @@ impl KeybindingResolver {
             Action::CopyWithTheme(theme) => t!("action.copy_with_theme", theme = theme).to_string(),
             Action::Cut => t!("action.cut").to_string(),
             Action::Paste => t!("action.paste").to_string(),
+            Action::PasteReindent => t!("action.paste_reindent").to_string(),
+            Action::PasteVerbatim => t!("action.paste_verbatim").to_string(),
+            Action::PasteFromHistory => t!("action.paste_from_history").to_string(),
+            Action::RetrySystemClipboard => t!("action.retry_system_clipboard").to_string(),
+            Action::CyclePaste => t!("action.cycle_paste").to_string(),
             Action::YankWordForward => t!("action.yank_word_forward").to_string(),
             Action::YankWordBackward => t!("action.yank_word_backward").to_string(),
             Action::YankToLineEnd => t!("action.yank_to_line_end").to_string(),
@@ -1627,14 +2532,49 @@ impl KeybindingResolver {
             Action::SaveAs => t!("action.save_as").to_string(),
             Action::Open => t!("action.open").to_string(),
             Action::SwitchProject => t!("action.switch_project").to_string(),
+            Action::OpenRecentProject => t!("action.open_recent_project").to_string(),
+            Action::TogglePinProject => t!("action.toggle_pin_project").to_string(),
+            Action::RestoreSessionSnapshot => t!("action.restore_session_snapshot").to_string(),
+            Action::SaveLayoutAs => t!("action.save_layout_as").to_string(),
+            Action::SwitchLayout(name) if name.is_empty() => t!("action.switch_layout").to_string(),
+            Action::SwitchLayout(name) => t!("action.switch_layout_named", name = name).to_string(),
+            Action::SwitchLayoutAt(index) => {
+                t!("action.switch_layout_at", index = index).to_string()
+            }
+            Action::DeleteLayout => t!("action.delete_layout").to_string(),
             Action::New => t!("action.new").to_string(),
             Action::Close => t!("action.close").to_string(),
             Action::CloseTab => t!("action.close_tab").to_string(),
+            Action::CloseSavedBuffers => t!("action.close_saved_buffers").to_string(),
             Action::Quit => t!("action.quit").to_string(),
             Action::Revert => t!("action.revert").to_string(),
+            Action::RevertBufferToSaved => t!("action.revert_buffer_to_saved").to_string(),
+            Action::CompareBufferWith => t!("action.compare_buffer_with").to_string(),
+            Action::CompareNextHunk => t!("action.compare_next_hunk").to_string(),
+            Action::ComparePreviousHunk => t!("action.compare_previous_hunk").to_string(),
+            Action::CompareCopyHunkToLeft => t!("action.compare_copy_hunk_to_left").to_string(),
+            Action::CompareCopyHunkToRight => t!("action.compare_copy_hunk_to_right").to_string(),
+            Action::CompareWithClipboard => t!("action.compare_with_clipboard").to_string(),
+            Action::CompareDirectories => t!("action.compare_directories").to_string(),
+            Action::DirCompareOpen => t!("action.dir_compare_open").to_string(),
+            Action::DirCompareToggleIdentical => {
+                t!("action.dir_compare_toggle_identical").to_string()
+            }
+            Action::DirCompareRefresh => t!("action.dir_compare_refresh").to_string(),
             Action::ToggleAutoRevert => t!("action.toggle_auto_revert").to_string(),
+            Action::ToggleBufferAutoRevert => t!("action.toggle_buffer_auto_revert").to_string(),
+            Action::ToggleFollowTail => t!("action.toggle_follow_tail").to_string(),
+            Action::TogglePinBuffer => t!("action.toggle_pin_buffer").to_string(),
+            Action::EditAnyway => t!("action.edit_anyway").to_string(),
+            Action::UnlockProtectedFile => t!("action.unlock_protected_file").to_string(),
             Action::FormatBuffer => t!("action.format_buffer").to_string(),
+            Action::RunSaveActions => t!("action.run_save_actions").to_string(),
             Action::GotoLine => t!("action.goto_line").to_string(),
+            Action::GotoByteOffset => t!("action.goto_byte_offset").to_string(),
+            Action::GotoPercentage => t!("action.goto_percentage").to_string(),
+            Action::CopyDataPath => t!("action.copy_data_path").to_string(),
+            Action::GotoDataPath => t!("action.goto_data_path").to_string(),
+            Action::SelectDataValue => t!("action.select_data_value").to_string(),
             Action::GoToMatchingBracket => t!("action.goto_matching_bracket").to_string(),
             Action::JumpToNextError => t!("action.jump_to_next_error").to_string(),
             Action::JumpToPreviousError => t!("action.jump_to_previous_error").to_string(),
@@ -1668,14 +2608,38 @@ impl KeybindingResolver {
             Action::Redo => t!("action.redo").to_string(),
             Action::ScrollUp => t!("action.scroll_up").to_string(),
             Action::ScrollDown => t!("action.scroll_down").to_string(),
+            Action::ScrollUpKeepCursor => t!("action.scroll_up_keep_cursor").to_string(),
+            Action::ScrollDownKeepCursor => t!("action.scroll_down_keep_cursor").to_string(),
+            Action::ScrollHalfPageUp => t!("action.scroll_half_page_up").to_string(),
+            Action::ScrollHalfPageDown => t!("action.scroll_half_page_down").to_string(),
+            Action::CursorLineToTop => t!("action.cursor_line_to_top").to_string(),
+            Action::CursorLineToCenter => t!("action.cursor_line_to_center").to_string(),
+            Action::CursorLineToBottom => t!("action.cursor_line_to_bottom").to_string(),
             Action::ShowHelp => t!("action.show_help").to_string(),
             Action::ShowKeyboardShortcuts => t!("action.show_keyboard_shortcuts").to_string(),
+            Action::KeyboardShortcutsFilter => t!("action.keyboard_shortcuts_filter").to_string(),
             Action::ShowWarnings => t!("action.show_warnings").to_string(),
+            Action::ReviewBlockedCommands => t!("action.review_blocked_commands").to_string(),
+            Action::CancelProgress => t!("action.cancel_progress").to_string(),
             Action::ShowLspStatus => t!("action.show_lsp_status").to_string(),
+            Action::UpdateNow => t!("action.update_now").to_string(),
+            Action::ShowSessionOwnership => t!("action.show_session_ownership").to_string(),
             Action::ClearWarnings => t!("action.clear_warnings").to_string(),
+            Action::RetryPluginInit => t!("action.retry_plugin_init").to_string(),
+            Action::FocusNotifications => t!("action.focus_notifications").to_string(),
+            Action::ShowNotificationHistory => t!("action.show_notification_history").to_string(),
+            Action::ShowUsageStats => t!("action.show_usage_stats").to_string(),
+            Action::ExportUsageStats => t!("action.export_usage_stats").to_string(),
+            Action::ExportSettingsArchive => t!("action.export_settings_archive").to_string(),
+            Action::ImportSettingsArchive => t!("action.import_settings_archive").to_string(),
+            Action::ShowFullMessage => t!("action.show_full_message").to_string(),
+            Action::OpenEditorLog => t!("action.open_editor_log").to_string(),
+            Action::FilterEditorLog => t!("action.filter_editor_log").to_string(),
+            Action::OpenEditorLogAtEntry => t!("action.open_editor_log_at_entry").to_string(),
             Action::CommandPalette => t!("action.command_palette").to_string(),
             Action::ToggleLineWrap => t!("action.toggle_line_wrap").to_string(),
             Action::ToggleComposeMode => t!("action.toggle_compose_mode").to_string(),
+            Action::ToggleDistractionFree => t!("action.toggle_distraction_free").to_string(),
             Action::SetComposeWidth => t!("action.set_compose_width").to_string(),
             Action::NextBuffer => t!("action.next_buffer").to_string(),
             Action::PrevBuffer => t!("action.prev_buffer").to_string(),
@@ -1689,7 +2653,9 @@ impl KeybindingResolver {
             Action::IncreaseSplitSize => t!("action.increase_split_size").to_string(),
             Action::DecreaseSplitSize => t!("action.decrease_split_size").to_string(),
             Action::ToggleMaximizeSplit => t!("action.toggle_maximize_split").to_string(),
+            Action::TogglePaneLock => t!("action.toggle_pane_lock").to_string(),
             Action::PromptConfirm => t!("action.prompt_confirm").to_string(),
+            Action::PromptConfirmDryRun => t!("action.prompt_confirm_dry_run").to_string(),
             Action::PromptCancel => t!("action.prompt_cancel").to_string(),
             Action::PromptBackspace => t!("action.prompt_backspace").to_string(),
             Action::PromptDelete => t!("action.prompt_delete").to_string(),
@@ -1752,26 +2718,45 @@ impl KeybindingResolver {
             Action::FileExplorerToggleGitignored => {
                 t!("action.file_explorer_toggle_gitignored").to_string()
             }
+            Action::FileExplorerToggleDetails => {
+                t!("action.file_explorer_toggle_details").to_string()
+            }
+            Action::FileExplorerCycleSort => t!("action.file_explorer_cycle_sort").to_string(),
+            Action::FileExplorerShowAll => t!("action.file_explorer_show_all").to_string(),
+            Action::FileExplorerContextMenu => t!("action.file_explorer_context_menu").to_string(),
             Action::LspCompletion => t!("action.lsp_completion").to_string(),
             Action::LspGotoDefinition => t!("action.lsp_goto_definition").to_string(),
             Action::LspReferences => t!("action.lsp_references").to_string(),
             Action::LspRename => t!("action.lsp_rename").to_string(),
+            Action::RenameInBuffer => t!("action.rename_in_buffer").to_string(),
             Action::LspHover => t!("action.lsp_hover").to_string(),
             Action::LspSignatureHelp => t!("action.lsp_signature_help").to_string(),
             Action::LspCodeActions => t!("action.lsp_code_actions").to_string(),
+            Action::BatchCodeAction => t!("action.batch_code_action").to_string(),
             Action::LspRestart => t!("action.lsp_restart").to_string(),
             Action::LspStop => t!("action.lsp_stop").to_string(),
             Action::ToggleInlayHints => t!("action.toggle_inlay_hints").to_string(),
             Action::ToggleMouseHover => t!("action.toggle_mouse_hover").to_string(),
+            Action::ToggleInlineBlame => t!("action.toggle_inline_blame").to_string(),
+            Action::ToggleInlineBlameAll => t!("action.toggle_inline_blame_all").to_string(),
+            Action::ShowBlameCommit => t!("action.show_blame_commit").to_string(),
             Action::ToggleLineNumbers => t!("action.toggle_line_numbers").to_string(),
             Action::ToggleMouseCapture => t!("action.toggle_mouse_capture").to_string(),
             Action::ToggleDebugHighlights => t!("action.toggle_debug_highlights").to_string(),
+            Action::ToggleInputLatencyHud => t!("action.toggle_input_latency_hud").to_string(),
+            Action::ShowInputLayers => t!("action.show_input_layers").to_string(),
+            Action::FreeCaches => t!("action.free_caches").to_string(),
+            Action::CompactBuffer => t!("action.compact_buffer").to_string(),
+            Action::CompactAllBuffers => t!("action.compact_all_buffers").to_string(),
+            Action::BufferMemoryReport => t!("action.buffer_memory_report").to_string(),
             Action::SetBackground => t!("action.set_background").to_string(),
             Action::SetBackgroundBlend => t!("action.set_background_blend").to_string(),
             Action::SetTabSize => t!("action.set_tab_size").to_string(),
             Action::SetLineEnding => t!("action.set_line_ending").to_string(),
             Action::ToggleIndentationStyle => t!("action.toggle_indentation_style").to_string(),
             Action::ToggleTabIndicators => t!("action.toggle_tab_indicators").to_string(),
+            Action::ToggleRenderWhitespace => t!("action.toggle_render_whitespace").to_string(),
+            Action::ToggleAnsiColors => t!("action.toggle_ansi_colors").to_string(),
             Action::ResetBufferSettings => t!("action.reset_buffer_settings").to_string(),
             Action::DumpConfig => t!("action.dump_config").to_string(),
             Action::Search => t!("action.search").to_string(),
@@ -1780,6 +2765,7 @@ impl KeybindingResolver {
             Action::FindPrevious => t!("action.find_previous").to_string(),
             Action::FindSelectionNext => t!("action.find_selection_next").to_string(),
             Action::FindSelectionPrevious => t!("action.find_selection_previous").to_string(),
+            Action::SearchInAllSplits => t!("action.search_in_all_splits").to_string(),
             Action::Replace => t!("action.replace").to_string(),
             Action::QueryReplace => t!("action.query_replace").to_string(),
             Action::MenuActivate => t!("action.menu_activate").to_string(),
@@ -1794,9 +2780,13 @@ impl KeybindingResolver {
                 t!("action.switch_keybinding_map", map = map).to_string()
             }
             Action::PluginAction(name) => t!("action.plugin_action", name = name).to_string(),
+            Action::RunCommand(name, _) => t!("action.run_command", name = name).to_string(),
             Action::ScrollTabsLeft => t!("action.scroll_tabs_left").to_string(),
             Action::ScrollTabsRight => t!("action.scroll_tabs_right").to_string(),
             Action::SelectTheme => t!("action.select_theme").to_string(),
+            Action::ReloadTheme => t!("action.reload_theme").to_string(),
+            Action::ToggleLightDarkTheme => t!("action.toggle_light_dark_theme").to_string(),
+            Action::CheckThemeContrast => t!("action.check_theme_contrast").to_string(),
             Action::SelectKeybindingMap => t!("action.select_keybinding_map").to_string(),
             Action::SelectCursorStyle => t!("action.select_cursor_style").to_string(),
             Action::SelectLocale => t!("action.select_locale").to_string(),
@@ -1820,8 +2810,34 @@ impl KeybindingResolver {
             Action::SettingsDecrement => t!("action.settings_decrement").to_string(),
             Action::ShellCommand => t!("action.shell_command").to_string(),
             Action::ShellCommandReplace => t!("action.shell_command_replace").to_string(),
+            Action::InsertFile => t!("action.insert_file").to_string(),
+            Action::InsertCommandOutput => t!("action.insert_command_output").to_string(),
+            Action::ToggleCommandStdin => t!("action.toggle_command_stdin").to_string(),
             Action::ToUpperCase => t!("action.to_uppercase").to_string(),
             Action::ToLowerCase => t!("action.to_lowercase").to_string(),
+            Action::ToTitleCase => t!("action.to_title_case").to_string(),
+            Action::ToCamelCase => t!("action.to_camel_case").to_string(),
+            Action::ToSnakeCase => t!("action.to_snake_case").to_string(),
+            Action::SortLines => t!("action.sort_lines").to_string(),
+            Action::UniqueLines => t!("action.unique_lines").to_string(),
+            Action::UniqueAdjacentLines => t!("action.unique_adjacent_lines").to_string(),
+            Action::ReverseLines => t!("action.reverse_lines").to_string(),
+            Action::ShuffleLines => t!("action.shuffle_lines").to_string(),
+            Action::PasteAsColumn => t!("action.paste_as_column").to_string(),
+            Action::AlignSelections => t!("action.align_selections").to_string(),
+            Action::PendingEditsToggle => t!("action.pending_edits_toggle").to_string(),
+            Action::PendingEditsToggleFile => t!("action.pending_edits_toggle_file").to_string(),
+            Action::PendingEditsToggleExpand => {
+                t!("action.pending_edits_toggle_expand").to_string()
+            }
+            Action::PendingEditsApply => t!("action.pending_edits_apply").to_string(),
+            Action::PendingEditsCancel => t!("action.pending_edits_cancel").to_string(),
+            Action::TodosList => t!("action.todos_list").to_string(),
+            Action::TodosRefresh => t!("action.todos_refresh").to_string(),
+            Action::TodosOpen => t!("action.todos_open").to_string(),
+            Action::WorkspaceDiagnostics => t!("action.workspace_diagnostics").to_string(),
+            Action::WorkspaceDiagnosticsOpen => t!("action.workspace_diagnostics_open").to_string(),
+            Action::RemoteDirOpen => t!("action.remote_dir_open").to_string(),
             Action::None => t!("action.none").to_string(),
         }
     }
@@ -1909,24 +2925,10 @@ impl KeybindingResolver {
     /// Reload bindings from config (for hot reload)
     pub fn reload(&mut self, config: &Config) {
         self.bindings.clear();
-        for binding in &config.keybindings {
-            if let Some(key_code) = Self::parse_key(&binding.key) {
-                let modifiers = Self::parse_modifiers(&binding.modifiers);
-                if let Some(action) = Action::from_str(&binding.action, &binding.args) {
-                    // Determine context from "when" clause
-                    let context = if let Some(ref when) = binding.when {
-                        KeyContext::from_when_clause(when).unwrap_or(KeyContext::Normal)
-                    } else {
-                        KeyContext::Normal
-                    };
-
-                    self.bindings
-                        .entry(context)
-                        .or_insert_with(HashMap::new)
-                        .insert((key_code, modifiers), action);
-                }
-            }
-        }
+        self.chord_bindings.clear();
+        self.records
+            .retain(|record| record.source != BindingSource::User);
+        self.load_bindings_from_vec(&config.keybindings);
     }
 }
 
@@ -2173,6 +3175,63 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_keymap_user_binding_overrides_default() {
+        use crate::config::Keybinding;
+
+        let mut config = Config::default();
+        config.keybindings.push(Keybinding {
+            key: "k".to_string(),
+            modifiers: vec!["ctrl".to_string()],
+            keys: vec![],
+            action: "command_palette".to_string(),
+            args: HashMap::new(),
+            when: None,
+        });
+        let resolver = KeybindingResolver::new(&config);
+        let ctrl_k = vec![(KeyCode::Char('k'), KeyModifiers::CONTROL)];
+
+        let keymap = resolver.keymap(KeyContext::Normal);
+        let entry = keymap.iter().find(|e| e.keys == ctrl_k).unwrap();
+        assert_eq!(entry.action, "command_palette");
+        assert_eq!(entry.source, BindingSource::User);
+        assert_eq!(entry.shadowed.len(), 1);
+        assert_eq!(entry.shadowed[0].action, "delete_to_line_end");
+        assert!(matches!(entry.shadowed[0].source, BindingSource::Keymap(_)));
+        assert!(entry.is_conflict());
+
+        // The user binding is for normal mode only, so prompts keep their default
+        let keymap = resolver.keymap(KeyContext::Prompt);
+        let entry = keymap.iter().find(|e| e.keys == ctrl_k).unwrap();
+        assert_eq!(entry.action, "prompt_delete_to_line_end");
+        assert!(matches!(entry.source, BindingSource::Keymap(_)));
+        assert!(entry.shadowed.is_empty());
+    }
+
+    #[test]
+    fn test_keymap_agrees_with_resolve() {
+        let resolver = KeybindingResolver::new(&Config::default());
+        for context in [
+            KeyContext::Normal,
+            KeyContext::Prompt,
+            KeyContext::FileExplorer,
+        ] {
+            for entry in resolver.keymap(context) {
+                let [(code, modifiers)] = entry.keys[..] else {
+                    continue;
+                };
+                let action = resolver.resolve(&KeyEvent::new(code, modifiers), context);
+                assert_eq!(
+                    KeybindingResolver::format_action(&action),
+                    entry.description,
+                    "{} in {:?}",
+                    entry.key_display(),
+                    context
+                );
+            }
+        }
+    }
+
     #[test]
     fn test_all_context_default_bindings_exist() {
         let config = Config::default();
@@ -4,11 +4,17 @@
 
 pub mod actions;
 pub mod buffer_mode;
+pub mod command_args;
 pub mod command_registry;
 pub mod commands;
+pub mod focus_stack;
 pub mod fuzzy;
 pub mod handler;
 pub mod input_history;
 pub mod keybindings;
 pub mod multi_cursor;
+pub mod paste_indent;
+pub mod pipeline;
 pub mod position_history;
+pub mod selection_expansion;
+pub mod terminal_reply;
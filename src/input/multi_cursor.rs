@@ -3,6 +3,9 @@
 use crate::model::cursor::Cursor;
 use crate::state::EditorState;
 
+/// Most cursors a buffer can have; adding more fails with a message
+pub const MAX_CURSORS: usize = 1000;
+
 /// Result of attempting to add a cursor
 pub enum AddCursorResult {
     /// Cursor was added successfully
@@ -38,8 +41,14 @@ fn cursor_position_on_line(line_start: usize, line_content: &str, target_col: us
     line_start + target_col.min(line_len)
 }
 
-/// Create a successful AddCursorResult
+/// Create a successful AddCursorResult, or fail if the buffer already has
+/// [`MAX_CURSORS`] cursors
 fn success_result(cursor: Cursor, state: &EditorState) -> AddCursorResult {
+    if state.cursors.count() >= MAX_CURSORS {
+        return AddCursorResult::Failed {
+            message: format!("Too many cursors (at most {})", MAX_CURSORS),
+        };
+    }
     AddCursorResult::Success {
         cursor,
         total_cursors: state.cursors.iter().count() + 1,
@@ -109,6 +118,11 @@ pub fn add_cursor_at_next_match(state: &mut EditorState) -> AddCursorResult {
     success_result(new_cursor, state)
 }
 
+/// Add a cursor at a byte position, e.g. where the user clicked
+pub fn add_cursor_at(state: &EditorState, position: usize) -> AddCursorResult {
+    success_result(Cursor::new(position), state)
+}
+
 /// Add a cursor above the primary cursor at the same column
 pub fn add_cursor_above(state: &mut EditorState) -> AddCursorResult {
     let position = state.cursors.primary().position;
@@ -0,0 +1,145 @@
+//! Reindenting pasted text to fit where it lands
+//!
+//! A pasted block keeps the shape of its indentation: the indent its lines
+//! share is taken off, and the indent of the place it's pasted into is put
+//! on instead, in the buffer's tabs or spaces. Blank lines come out empty.
+
+use crate::input::actions::indent_to_string;
+
+/// How the first pasted line sits on the line it's pasted into
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FirstLine {
+    /// Pasted after other text of the line: it goes in as it is, and the
+    /// lines after it set the indent to take off
+    InPlace,
+    /// Pasted after only the first `column` columns of the line's
+    /// indentation, which it keeps
+    AtIndent(usize),
+}
+
+/// Width in columns of the leading whitespace of a line, a tab counting as
+/// `tab_size`
+pub fn indent_width(line: &str, tab_size: usize) -> usize {
+    line.chars()
+        .take_while(|c| *c == ' ' || *c == '\t')
+        .map(|c| if c == '\t' { tab_size } else { 1 })
+        .sum()
+}
+
+fn is_blank(line: &str) -> bool {
+    line.trim_matches([' ', '\t']).is_empty()
+}
+
+/// The indent the non-blank lines share, skipping the first `skip` lines
+fn base_indent(text: &str, skip: usize, tab_size: usize) -> usize {
+    text.split('\n')
+        .skip(skip)
+        .filter(|line| !is_blank(line))
+        .map(|line| indent_width(line, tab_size))
+        .min()
+        .unwrap_or(0)
+}
+
+/// Reindent `text`, with lines separated by `\n`, to `indent` columns
+pub fn reindent(
+    text: &str,
+    indent: usize,
+    first_line: FirstLine,
+    use_tabs: bool,
+    tab_size: usize,
+) -> String {
+    let skip = usize::from(first_line == FirstLine::InPlace);
+    let base = base_indent(text, skip, tab_size);
+    text.split('\n')
+        .enumerate()
+        .map(|(index, line)| {
+            if index == 0 && first_line == FirstLine::InPlace {
+                return line.to_string();
+            }
+            if is_blank(line) {
+                return String::new();
+            }
+            let mut width = indent + indent_width(line, tab_size) - base;
+            if let (0, FirstLine::AtIndent(column)) = (index, first_line) {
+                width = width.saturating_sub(column);
+            }
+            let content = line.trim_start_matches([' ', '\t']);
+            format!("{}{}", indent_to_string(width, use_tabs, tab_size), content)
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_indent_width_counts_tabs_as_tab_size() {
+        assert_eq!(indent_width("    x", 4), 4);
+        assert_eq!(indent_width("\t  x", 4), 6);
+        assert_eq!(indent_width(" \tx", 8), 9);
+        assert_eq!(indent_width("x", 4), 0);
+    }
+
+    #[test]
+    fn test_shared_indent_is_replaced() {
+        let text = "        if a {\n            b();\n        }";
+        assert_eq!(
+            reindent(text, 4, FirstLine::AtIndent(0), false, 4),
+            "    if a {\n        b();\n    }"
+        );
+    }
+
+    #[test]
+    fn test_first_line_keeps_the_indent_it_is_pasted_after() {
+        let text = "        a\n            b";
+        assert_eq!(
+            reindent(text, 4, FirstLine::AtIndent(4), false, 4),
+            "a\n        b"
+        );
+        // Pasted after more indentation than the target, it isn't undone
+        assert_eq!(
+            reindent(text, 4, FirstLine::AtIndent(8), false, 4),
+            "a\n        b"
+        );
+    }
+
+    #[test]
+    fn test_pasting_mid_line_leaves_the_first_line() {
+        // Copied from after the indentation of a deeply nested line
+        let text = "call(\n            arg,\n        )";
+        assert_eq!(
+            reindent(text, 4, FirstLine::InPlace, false, 4),
+            "call(\n        arg,\n    )"
+        );
+    }
+
+    #[test]
+    fn test_blank_lines_come_out_empty() {
+        let text = "    a\n  \t\n\n    b\n";
+        assert_eq!(
+            reindent(text, 2, FirstLine::AtIndent(0), false, 4),
+            "  a\n\n\n  b\n"
+        );
+    }
+
+    #[test]
+    fn test_tabs_and_spaces_follow_the_buffer() {
+        // A tab and four spaces are the same indent
+        let text = "\tif a {\n\t    b();\n    }";
+        assert_eq!(
+            reindent(text, 8, FirstLine::AtIndent(0), true, 4),
+            "\t\tif a {\n\t\t\tb();\n\t\t}"
+        );
+        assert_eq!(
+            reindent(text, 2, FirstLine::AtIndent(0), false, 4),
+            "  if a {\n      b();\n  }"
+        );
+        // Widths that aren't a whole number of tabs end in spaces
+        assert_eq!(
+            reindent("a\n  b", 4, FirstLine::AtIndent(0), true, 4),
+            "\ta\n\t  b"
+        );
+    }
+}
@@ -0,0 +1,556 @@
+//! Key and action pipeline
+//!
+//! A key event that reaches normal editing (no layer of the
+//! [focus stack](crate::input::focus_stack) above the keymaps took it) goes
+//! through these stages, in order:
+//!
+//! 1. **Keymap resolution** ([`resolve_key`]): the key, together with any
+//!    keys pending from an unfinished chord, is offered to each
+//!    [`KeymapLayer`] of the context stack, highest priority first: the
+//!    global editor mode set by a plugin, the active buffer's mode, the
+//!    read-only guard of the editor mode, then the user and default
+//!    keybindings of the current context. The first layer that claims the
+//!    key decides what happens to it.
+//! 2. **Pre-action hooks** ([`ActionHost::before_action`]): may veto the
+//!    action. A vetoed action goes no further.
+//! 3. **Recording** ([`ActionHost::record_action`]): the macro recorder sees
+//!    only actions that passed the pre-action hooks.
+//! 4. **Execution** ([`ActionHost::execute_action`]).
+//! 5. **Post-action hooks** ([`ActionHost::after_action`]): run only when
+//!    execution succeeded.
+//!
+//! Stages 2 to 5 ([`run_action`]) apply to every action, whether it came
+//! from a key, the command palette, a macro or a plugin.
+
+use crate::input::buffer_mode::ModeRegistry;
+use crate::input::keybindings::{Action, ChordResolution, KeyContext, KeybindingResolver};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::collections::HashMap;
+
+/// Keys typed so far towards an unfinished chord
+pub type ChordState = Vec<(KeyCode, KeyModifiers)>;
+
+/// What a single keymap layer makes of a key
+#[derive(Debug, Clone, PartialEq)]
+pub enum LayerResolution {
+    /// The key (with the pending chord) completes a binding
+    Action(Action),
+    /// The key extends a chord this layer knows; wait for more keys
+    Prefix,
+    /// The layer claims the key but does nothing with it
+    Swallow,
+    /// Not bound here; ask the next layer
+    Pass,
+}
+
+/// One layer of the keymap context stack
+pub trait KeymapLayer {
+    /// Resolve `code`/`modifiers` typed after the keys in `chord`
+    fn resolve(
+        &self,
+        chord: &[(KeyCode, KeyModifiers)],
+        code: KeyCode,
+        modifiers: KeyModifiers,
+    ) -> LayerResolution;
+}
+
+/// What the keymap stack makes of a key
+#[derive(Debug, Clone, PartialEq)]
+pub enum KeyResolution {
+    /// Run this action
+    Action(Action),
+    /// Part of a chord; nothing to run yet
+    Pending,
+    /// Nothing to run
+    Ignored,
+}
+
+/// Offer a key to each layer in turn, keeping `chord` up to date
+///
+/// The pending chord is extended when a layer reports a prefix and cleared
+/// once the key is resolved either way.
+pub fn resolve_key(
+    layers: &[&dyn KeymapLayer],
+    chord: &mut ChordState,
+    code: KeyCode,
+    modifiers: KeyModifiers,
+) -> KeyResolution {
+    resolve_key_with_layer(layers, chord, code, modifiers).0
+}
+
+/// [`resolve_key`], also giving the index of the layer that claimed the key
+pub fn resolve_key_with_layer(
+    layers: &[&dyn KeymapLayer],
+    chord: &mut ChordState,
+    code: KeyCode,
+    modifiers: KeyModifiers,
+) -> (KeyResolution, Option<usize>) {
+    for (index, layer) in layers.iter().enumerate() {
+        match layer.resolve(chord, code, modifiers) {
+            LayerResolution::Action(action) => {
+                chord.clear();
+                return (KeyResolution::Action(action), Some(index));
+            }
+            LayerResolution::Prefix => {
+                chord.push((code, modifiers));
+                return (KeyResolution::Pending, Some(index));
+            }
+            LayerResolution::Swallow => {
+                chord.clear();
+                return (KeyResolution::Ignored, Some(index));
+            }
+            LayerResolution::Pass => {}
+        }
+    }
+    if !chord.is_empty() {
+        tracing::debug!("Chord sequence abandoned, clearing state");
+        chord.clear();
+    }
+    (KeyResolution::Ignored, None)
+}
+
+/// Turn a mode binding's command name into an action
+fn mode_action(name: String) -> Action {
+    Action::from_str(&name, &HashMap::new()).unwrap_or(Action::PluginAction(name))
+}
+
+/// Bindings of a mode registered by a plugin
+///
+/// The global editor mode (e.g. "vi-normal") also resolves chords; a
+/// buffer's own mode only has single keys.
+pub struct ModeLayer<'a> {
+    pub registry: &'a ModeRegistry,
+    pub mode: &'a str,
+    pub chords: bool,
+}
+
+impl KeymapLayer for ModeLayer<'_> {
+    fn resolve(
+        &self,
+        chord: &[(KeyCode, KeyModifiers)],
+        code: KeyCode,
+        modifiers: KeyModifiers,
+    ) -> LayerResolution {
+        if self.chords {
+            if let Some(name) = self
+                .registry
+                .resolve_chord_keybinding(self.mode, chord, code, modifiers)
+            {
+                return LayerResolution::Action(mode_action(name));
+            }
+            if self
+                .registry
+                .is_chord_prefix(self.mode, chord, code, modifiers)
+            {
+                return LayerResolution::Prefix;
+            }
+        }
+        match self.registry.resolve_keybinding(self.mode, code, modifiers) {
+            Some(name) => LayerResolution::Action(mode_action(name)),
+            None => LayerResolution::Pass,
+        }
+    }
+}
+
+/// Ignores keys left unbound by a read-only editor mode
+///
+/// In a read-only mode (like vi-normal) an unbound key does nothing; in an
+/// editable one (like vi-insert) it falls through to the keybindings.
+pub struct ReadOnlyModeLayer<'a> {
+    pub registry: &'a ModeRegistry,
+    pub mode: &'a str,
+}
+
+impl KeymapLayer for ReadOnlyModeLayer<'_> {
+    fn resolve(
+        &self,
+        _: &[(KeyCode, KeyModifiers)],
+        _: KeyCode,
+        _: KeyModifiers,
+    ) -> LayerResolution {
+        if self.registry.is_read_only(self.mode) {
+            tracing::debug!("Ignoring unbound key in read-only mode {:?}", self.mode);
+            LayerResolution::Swallow
+        } else {
+            LayerResolution::Pass
+        }
+    }
+}
+
+/// The user and default keybindings for a context
+///
+/// Always claims the key: unbound keys resolve to [`Action::None`] (or a
+/// character insertion where the context allows it).
+pub struct KeybindingLayer<'a> {
+    pub resolver: &'a KeybindingResolver,
+    pub context: KeyContext,
+}
+
+impl KeymapLayer for KeybindingLayer<'_> {
+    fn resolve(
+        &self,
+        chord: &[(KeyCode, KeyModifiers)],
+        code: KeyCode,
+        modifiers: KeyModifiers,
+    ) -> LayerResolution {
+        let event = KeyEvent::new(code, modifiers);
+        match self.resolver.resolve_chord(chord, &event, self.context) {
+            ChordResolution::Complete(action) => LayerResolution::Action(action),
+            ChordResolution::Partial => LayerResolution::Prefix,
+            ChordResolution::NoMatch => {
+                LayerResolution::Action(self.resolver.resolve(&event, self.context))
+            }
+        }
+    }
+}
+
+/// What happened to an action sent through [`run_action`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ActionOutcome {
+    /// The action was executed
+    Executed,
+    /// A pre-action hook stopped the action
+    Vetoed,
+}
+
+/// The stages of running an action, supplied by the editor
+pub trait ActionHost {
+    /// Pre-action hooks; return false to veto the action
+    fn before_action(&mut self, action: &Action) -> bool;
+    /// Record the action (macro recording)
+    fn record_action(&mut self, action: &Action);
+    /// Apply the action to the model
+    fn execute_action(&mut self, action: Action) -> std::io::Result<()>;
+    /// Post-action hooks
+    fn after_action(&mut self, action: &Action);
+}
+
+/// Run an action through the pre-action hooks, recording, execution and
+/// post-action hooks, in that order
+pub fn run_action<H: ActionHost + ?Sized>(
+    host: &mut H,
+    action: Action,
+) -> std::io::Result<ActionOutcome> {
+    if !host.before_action(&action) {
+        tracing::debug!("Action vetoed by pre-action hook: {:?}", action);
+        return Ok(ActionOutcome::Vetoed);
+    }
+    host.record_action(&action);
+    host.execute_action(action.clone())?;
+    host.after_action(&action);
+    Ok(ActionOutcome::Executed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::input::buffer_mode::BufferMode;
+
+    const NONE: KeyModifiers = KeyModifiers::NONE;
+
+    fn vi_registry() -> ModeRegistry {
+        let mut registry = ModeRegistry::new();
+        registry.register(
+            BufferMode::new("vi-normal")
+                .with_read_only(true)
+                .with_binding(KeyCode::Char('j'), NONE, "move_down")
+                .with_binding(KeyCode::Char('x'), NONE, "vi:delete_char")
+                .with_chord_binding(
+                    vec![(KeyCode::Char('g'), NONE), (KeyCode::Char('g'), NONE)],
+                    "move_document_start",
+                ),
+        );
+        registry.register(BufferMode::new("vi-insert"));
+        registry
+    }
+
+    /// Layers as the editor stacks them for a plugin-set editor mode
+    fn resolve_in_mode(
+        registry: &ModeRegistry,
+        resolver: &KeybindingResolver,
+        mode: &str,
+        chord: &mut ChordState,
+        code: KeyCode,
+    ) -> KeyResolution {
+        let mode_layer = ModeLayer {
+            registry,
+            mode,
+            chords: true,
+        };
+        let read_only = ReadOnlyModeLayer { registry, mode };
+        let keybindings = KeybindingLayer {
+            resolver,
+            context: KeyContext::Normal,
+        };
+        resolve_key(&[&mode_layer, &read_only, &keybindings], chord, code, NONE)
+    }
+
+    #[test]
+    fn test_keybindings_resolve_plain_keys() {
+        let resolver = KeybindingResolver::new(&Config::default());
+        let keybindings = KeybindingLayer {
+            resolver: &resolver,
+            context: KeyContext::Normal,
+        };
+        let mut chord = ChordState::new();
+
+        assert_eq!(
+            resolve_key(
+                &[&keybindings],
+                &mut chord,
+                KeyCode::Char('s'),
+                KeyModifiers::CONTROL
+            ),
+            KeyResolution::Action(Action::Save)
+        );
+        assert_eq!(
+            resolve_key(&[&keybindings], &mut chord, KeyCode::Char('a'), NONE),
+            KeyResolution::Action(Action::InsertChar('a'))
+        );
+    }
+
+    #[test]
+    fn test_keybinding_chord_waits_for_second_key() {
+        let mut config = Config::default();
+        config.keybindings.push(
+            serde_json::from_value(serde_json::json!({
+                "keys": [
+                    {"key": "k", "modifiers": ["ctrl"]},
+                    {"key": "d", "modifiers": []}
+                ],
+                "action": "delete_line",
+                "when": "normal"
+            }))
+            .unwrap(),
+        );
+        let resolver = KeybindingResolver::new(&config);
+        let keybindings = KeybindingLayer {
+            resolver: &resolver,
+            context: KeyContext::Normal,
+        };
+        let mut chord = ChordState::new();
+
+        assert_eq!(
+            resolve_key(
+                &[&keybindings],
+                &mut chord,
+                KeyCode::Char('k'),
+                KeyModifiers::CONTROL
+            ),
+            KeyResolution::Pending
+        );
+        assert_eq!(chord.len(), 1);
+        assert_eq!(
+            resolve_key(&[&keybindings], &mut chord, KeyCode::Char('d'), NONE),
+            KeyResolution::Action(Action::DeleteLine)
+        );
+        assert!(chord.is_empty());
+    }
+
+    #[test]
+    fn test_mode_chord_and_bindings_take_precedence() {
+        let registry = vi_registry();
+        let resolver = KeybindingResolver::new(&Config::default());
+        let mut chord = ChordState::new();
+
+        // "gg" jumps to the start instead of inserting two g's
+        assert_eq!(
+            resolve_in_mode(
+                &registry,
+                &resolver,
+                "vi-normal",
+                &mut chord,
+                KeyCode::Char('g')
+            ),
+            KeyResolution::Pending
+        );
+        assert_eq!(
+            resolve_in_mode(
+                &registry,
+                &resolver,
+                "vi-normal",
+                &mut chord,
+                KeyCode::Char('g')
+            ),
+            KeyResolution::Action(Action::MoveDocumentStart)
+        );
+
+        // Built-in names become actions, others plugin actions
+        assert_eq!(
+            resolve_in_mode(
+                &registry,
+                &resolver,
+                "vi-normal",
+                &mut chord,
+                KeyCode::Char('j')
+            ),
+            KeyResolution::Action(Action::MoveDown)
+        );
+        assert_eq!(
+            resolve_in_mode(
+                &registry,
+                &resolver,
+                "vi-normal",
+                &mut chord,
+                KeyCode::Char('x')
+            ),
+            KeyResolution::Action(Action::PluginAction("vi:delete_char".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_unbound_keys_in_read_only_mode_are_ignored() {
+        let registry = vi_registry();
+        let resolver = KeybindingResolver::new(&Config::default());
+        let mut chord = ChordState::new();
+
+        assert_eq!(
+            resolve_in_mode(
+                &registry,
+                &resolver,
+                "vi-normal",
+                &mut chord,
+                KeyCode::Char('z')
+            ),
+            KeyResolution::Ignored
+        );
+        // An abandoned chord is dropped along with the key
+        resolve_in_mode(
+            &registry,
+            &resolver,
+            "vi-normal",
+            &mut chord,
+            KeyCode::Char('g'),
+        );
+        assert_eq!(
+            resolve_in_mode(
+                &registry,
+                &resolver,
+                "vi-normal",
+                &mut chord,
+                KeyCode::Char('z')
+            ),
+            KeyResolution::Ignored
+        );
+        assert!(chord.is_empty());
+
+        // An editable mode lets typing through to the keybindings
+        assert_eq!(
+            resolve_in_mode(
+                &registry,
+                &resolver,
+                "vi-insert",
+                &mut chord,
+                KeyCode::Char('z')
+            ),
+            KeyResolution::Action(Action::InsertChar('z'))
+        );
+    }
+
+    #[test]
+    fn test_buffer_mode_binding_overrides_keybindings() {
+        let registry = ModeRegistry::new();
+        let resolver = KeybindingResolver::new(&Config::default());
+        let buffer_mode = ModeLayer {
+            registry: &registry,
+            mode: "special",
+            chords: false,
+        };
+        let keybindings = KeybindingLayer {
+            resolver: &resolver,
+            context: KeyContext::Normal,
+        };
+        let mut chord = ChordState::new();
+
+        assert_eq!(
+            resolve_key(
+                &[&buffer_mode, &keybindings],
+                &mut chord,
+                KeyCode::Char('q'),
+                NONE
+            ),
+            KeyResolution::Action(Action::Close)
+        );
+        assert_eq!(
+            resolve_key(
+                &[&buffer_mode, &keybindings],
+                &mut chord,
+                KeyCode::Left,
+                NONE
+            ),
+            KeyResolution::Action(Action::MoveLeft)
+        );
+    }
+
+    /// Records which stages ran, in order
+    #[derive(Default)]
+    struct RecordingHost {
+        vetoed: Vec<Action>,
+        fail: bool,
+        stages: Vec<String>,
+        macro_actions: Vec<Action>,
+    }
+
+    impl ActionHost for RecordingHost {
+        fn before_action(&mut self, action: &Action) -> bool {
+            self.stages.push("before".to_string());
+            !self.vetoed.contains(action)
+        }
+
+        fn record_action(&mut self, action: &Action) {
+            self.stages.push("record".to_string());
+            self.macro_actions.push(action.clone());
+        }
+
+        fn execute_action(&mut self, _action: Action) -> std::io::Result<()> {
+            self.stages.push("execute".to_string());
+            if self.fail {
+                Err(std::io::Error::other("failed"))
+            } else {
+                Ok(())
+            }
+        }
+
+        fn after_action(&mut self, _action: &Action) {
+            self.stages.push("after".to_string());
+        }
+    }
+
+    #[test]
+    fn test_run_action_stage_order() {
+        let mut host = RecordingHost::default();
+        let outcome = run_action(&mut host, Action::MoveLeft).unwrap();
+
+        assert_eq!(outcome, ActionOutcome::Executed);
+        assert_eq!(host.stages, vec!["before", "record", "execute", "after"]);
+        assert_eq!(host.macro_actions, vec![Action::MoveLeft]);
+    }
+
+    #[test]
+    fn test_vetoed_action_is_not_recorded_or_executed() {
+        let mut host = RecordingHost {
+            vetoed: vec![Action::InsertChar('x')],
+            ..Default::default()
+        };
+        let outcome = run_action(&mut host, Action::InsertChar('x')).unwrap();
+
+        assert_eq!(outcome, ActionOutcome::Vetoed);
+        assert_eq!(host.stages, vec!["before"]);
+        assert!(host.macro_actions.is_empty());
+
+        // Other actions still go through
+        run_action(&mut host, Action::InsertChar('y')).unwrap();
+        assert_eq!(host.macro_actions, vec![Action::InsertChar('y')]);
+    }
+
+    #[test]
+    fn test_failed_action_skips_post_hooks() {
+        let mut host = RecordingHost {
+            fail: true,
+            ..Default::default()
+        };
+        assert!(run_action(&mut host, Action::Save).is_err());
+        assert_eq!(host.stages, vec!["before", "record", "execute"]);
+    }
+}
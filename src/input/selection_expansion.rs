@@ -0,0 +1,262 @@
+//! Structural selection expansion without a syntax tree
+//!
+//! Selections grow through word → line → paragraph → indentation block →
+//! whole buffer, skipping steps that wouldn't contain the current selection.
+//! Each cursor remembers the steps it took, so shrinking retraces them
+//! exactly.
+
+use crate::model::buffer::Buffer;
+use crate::model::cursor::Cursor;
+use crate::model::event::CursorId;
+use crate::primitives::word_navigation::{find_word_end, find_word_start};
+use std::collections::HashMap;
+use std::ops::Range;
+
+/// One expansion step of a cursor: its selection before and after
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExpansionStep {
+    /// Where the ladder is measured from, the same for all steps of a cursor
+    origin: usize,
+    before_anchor: Option<usize>,
+    before_position: usize,
+    anchor: usize,
+    position: usize,
+}
+
+/// Expansion steps of each cursor, most recent last
+pub type ExpansionHistory = HashMap<CursorId, Vec<ExpansionStep>>;
+
+/// A buffer line: byte range including its line ending, and whether it is blank
+struct Line {
+    range: Range<usize>,
+    blank: bool,
+    indent: usize,
+}
+
+impl Line {
+    fn new(start: usize, text: &str, tab_size: usize) -> Self {
+        let mut indent = 0;
+        for ch in text.chars() {
+            match ch {
+                ' ' => indent += 1,
+                '\t' => indent += tab_size.max(1) - indent % tab_size.max(1),
+                _ => break,
+            }
+        }
+        Self {
+            range: start..start + text.len(),
+            blank: text.trim().is_empty(),
+            indent,
+        }
+    }
+}
+
+/// The line containing `position`
+fn line_at(
+    buffer: &mut Buffer,
+    position: usize,
+    tab_size: usize,
+    estimated_line_length: usize,
+) -> Option<Line> {
+    let mut iter = buffer.line_iterator(position, estimated_line_length);
+    iter.next()
+        .map(|(start, text)| Line::new(start, &text, tab_size))
+}
+
+/// The lines around `line` for which `include` holds, with blank lines at
+/// either end left out
+fn surrounding_lines(
+    buffer: &mut Buffer,
+    line: Line,
+    tab_size: usize,
+    estimated_line_length: usize,
+    include: impl Fn(&Line) -> bool,
+) -> Option<Range<usize>> {
+    let mut before = Vec::new();
+    let mut iter = buffer.line_iterator(line.range.start, estimated_line_length);
+    while let Some((start, text)) = iter.prev() {
+        let prev = Line::new(start, &text, tab_size);
+        if !include(&prev) {
+            break;
+        }
+        before.push(prev);
+    }
+
+    let mut after = Vec::new();
+    if line.range.end < buffer.len() {
+        let mut iter = buffer.line_iterator(line.range.end, estimated_line_length);
+        while let Some((start, text)) = iter.next() {
+            let next = Line::new(start, &text, tab_size);
+            if !include(&next) {
+                break;
+            }
+            after.push(next);
+        }
+    }
+
+    let lines: Vec<Line> = before
+        .into_iter()
+        .rev()
+        .chain(std::iter::once(line))
+        .chain(after)
+        .collect();
+    let first = lines.iter().find(|l| !l.blank)?;
+    let last = lines.iter().rev().find(|l| !l.blank)?;
+    Some(first.range.start..last.range.end)
+}
+
+/// The word at `position`
+pub fn word_range(buffer: &Buffer, position: usize) -> Option<Range<usize>> {
+    let start = find_word_start(buffer, position);
+    let end = find_word_end(buffer, start);
+    (start < end).then_some(start..end)
+}
+
+/// The line containing `position`, including its line ending
+pub fn line_range(
+    buffer: &mut Buffer,
+    position: usize,
+    estimated_line_length: usize,
+) -> Option<Range<usize>> {
+    line_at(buffer, position, 1, estimated_line_length).map(|line| line.range)
+}
+
+/// The block of contiguous non-blank lines around `position`; none on a
+/// blank line
+pub fn paragraph_range(
+    buffer: &mut Buffer,
+    position: usize,
+    estimated_line_length: usize,
+) -> Option<Range<usize>> {
+    let line = line_at(buffer, position, 1, estimated_line_length)?;
+    if line.blank {
+        return None;
+    }
+    surrounding_lines(buffer, line, 1, estimated_line_length, |l| !l.blank)
+}
+
+/// The contiguous lines indented at least as deep as the line at `position`
+///
+/// Blank lines inside the block belong to it. On a blank line the indent of
+/// the next non-blank line (or the previous one, at the end of the buffer)
+/// is used.
+pub fn indentation_block_range(
+    buffer: &mut Buffer,
+    position: usize,
+    tab_size: usize,
+    estimated_line_length: usize,
+) -> Option<Range<usize>> {
+    let line = line_at(buffer, position, tab_size, estimated_line_length)?;
+    let indent = if line.blank {
+        let mut iter = buffer.line_iterator(line.range.start, estimated_line_length);
+        let below = std::iter::from_fn(|| iter.next())
+            .map(|(start, text)| Line::new(start, &text, tab_size))
+            .find(|l| !l.blank)
+            .map(|l| l.indent);
+        match below {
+            Some(indent) => indent,
+            None => {
+                let mut iter = buffer.line_iterator(line.range.start, estimated_line_length);
+                std::iter::from_fn(|| iter.prev())
+                    .map(|(start, text)| Line::new(start, &text, tab_size))
+                    .find(|l| !l.blank)?
+                    .indent
+            }
+        }
+    } else {
+        line.indent
+    };
+    surrounding_lines(buffer, line, tab_size, estimated_line_length, |l| {
+        l.blank || l.indent >= indent
+    })
+}
+
+/// The next step of the ladder around `origin`: the smallest of its word,
+/// line, paragraph, indentation block and the whole buffer that strictly
+/// contains `current`
+///
+/// A paragraph and an indentation block need not nest; whichever doesn't
+/// contain the other's selection is skipped.
+fn expand_range(
+    buffer: &mut Buffer,
+    origin: usize,
+    current: &Range<usize>,
+    tab_size: usize,
+    estimated_line_length: usize,
+) -> Option<Range<usize>> {
+    let candidates = [
+        word_range(buffer, origin),
+        line_range(buffer, origin, estimated_line_length),
+        paragraph_range(buffer, origin, estimated_line_length),
+        indentation_block_range(buffer, origin, tab_size, estimated_line_length),
+        Some(0..buffer.len()),
+    ];
+    candidates
+        .into_iter()
+        .flatten()
+        .filter(|range| {
+            range.start <= current.start && range.end >= current.end && range.len() > current.len()
+        })
+        .min_by_key(|range| range.len())
+}
+
+/// Steps of a cursor that still describe its selection; steps left over from
+/// a selection the cursor has since moved away from are dropped
+fn current_steps<'a>(
+    history: &'a mut ExpansionHistory,
+    cursor_id: CursorId,
+    cursor: &Cursor,
+) -> &'a mut Vec<ExpansionStep> {
+    let steps = history.entry(cursor_id).or_default();
+    let matches = steps
+        .last()
+        .is_some_and(|step| cursor.anchor == Some(step.anchor) && cursor.position == step.position);
+    if !matches {
+        steps.clear();
+    }
+    steps
+}
+
+/// Grow the selection of a cursor one step up the ladder
+///
+/// Returns the new (anchor, position), with the anchor at the start so a
+/// later expansion keeps growing from the same place.
+pub fn expand(
+    history: &mut ExpansionHistory,
+    buffer: &mut Buffer,
+    cursor_id: CursorId,
+    cursor: &Cursor,
+    tab_size: usize,
+    estimated_line_length: usize,
+) -> Option<(usize, usize)> {
+    let steps = current_steps(history, cursor_id, cursor);
+    let current = cursor
+        .selection_range()
+        .unwrap_or(cursor.position..cursor.position);
+    let origin = steps
+        .first()
+        .map(|step| step.origin)
+        .unwrap_or(current.start);
+    let range = expand_range(buffer, origin, &current, tab_size, estimated_line_length)?;
+    steps.push(ExpansionStep {
+        origin,
+        before_anchor: cursor.anchor,
+        before_position: cursor.position,
+        anchor: range.start,
+        position: range.end,
+    });
+    Some((range.start, range.end))
+}
+
+/// Undo the last expansion of a cursor
+///
+/// Returns the (anchor, position) it had before, or None when its selection
+/// didn't come from expanding.
+pub fn shrink(
+    history: &mut ExpansionHistory,
+    cursor_id: CursorId,
+    cursor: &Cursor,
+) -> Option<(Option<usize>, usize)> {
+    let step = current_steps(history, cursor_id, cursor).pop()?;
+    Some((step.before_anchor, step.before_position))
+}
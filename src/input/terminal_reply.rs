@@ -0,0 +1,293 @@
+//! Terminal replies that arrive as key presses
+//!
+//! A terminal answers the OSC 11 query for its background color on stdin,
+//! like typed input: `ESC ] 11;rgb:RRRR/GGGG/BBBB` ended by BEL or by
+//! `ESC \`. Crossterm has no event for it and reads it as the keys Alt+],
+//! the characters of the reply, and Ctrl+G or Alt+\.
+//!
+//! While a query is unanswered, [`ReplyReader`] holds back keys that could
+//! be such a reply and gives them back as soon as they stop matching one,
+//! or when the rest of the reply doesn't follow in time. Nothing waits for
+//! the terminal: one that answers late or not at all costs at most a held
+//! Alt+].
+
+use std::time::{Duration, Instant};
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::style::Color;
+
+use crate::view::theme::Appearance;
+
+/// How long a query waits for its reply; slow links can take a while
+pub const QUERY_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// How long the keys of a started reply are held for the rest of it, which
+/// the terminal writes all at once
+pub const REPLY_TIMEOUT: Duration = Duration::from_millis(250);
+
+/// Longest reply read, `11;rgba:` and four channels of four digits
+const MAX_REPLY_LEN: usize = 32;
+
+/// What a key press turned out to be
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReadKey {
+    /// Keys to handle as typed: the key itself, or held keys that turned out
+    /// not to be a reply
+    Keys(Vec<KeyEvent>),
+    /// Part of a reply, to wait for the rest of
+    Held,
+    /// A whole reply, with the background it reports if it could be read
+    Reply(Option<Appearance>),
+}
+
+/// Picks background color replies out of the key presses
+#[derive(Debug, Default)]
+pub struct ReplyReader {
+    /// Until when a reply to the last query is taken
+    awaiting_until: Option<Instant>,
+    /// Keys of the reply read so far, from its Alt+]
+    held: Vec<KeyEvent>,
+    /// When the reply started
+    held_since: Option<Instant>,
+    /// The reply's text after `ESC ]`
+    text: String,
+}
+
+impl ReplyReader {
+    /// Note that a background color query was sent at `now`
+    pub fn expect_reply(&mut self, now: Instant) {
+        self.awaiting_until = Some(now + QUERY_TIMEOUT);
+    }
+
+    /// Whether a query is waiting for its reply at `now`
+    pub fn is_awaiting(&self, now: Instant) -> bool {
+        self.awaiting_until.is_some_and(|until| now < until)
+    }
+
+    /// Read a key press at `now`
+    pub fn read_key(&mut self, key: KeyEvent, now: Instant) -> ReadKey {
+        if self.held.is_empty() {
+            if key.code == KeyCode::Char(']')
+                && key.modifiers == KeyModifiers::ALT
+                && self.is_awaiting(now)
+            {
+                self.held.push(key);
+                self.held_since = Some(now);
+                return ReadKey::Held;
+            }
+            return ReadKey::Keys(vec![key]);
+        }
+
+        let ends = matches!(
+            (key.code, key.modifiers),
+            (KeyCode::Char('g'), KeyModifiers::CONTROL) | (KeyCode::Char('\\'), KeyModifiers::ALT)
+        );
+        if ends && self.text.starts_with("11;") {
+            let appearance = parse_background_reply(&self.text[3..]);
+            self.clear();
+            self.awaiting_until = None;
+            return ReadKey::Reply(appearance);
+        }
+
+        let c = match key.code {
+            KeyCode::Char(c)
+                if KeyModifiers::SHIFT.contains(key.modifiers) && c.is_ascii_graphic() =>
+            {
+                Some(c)
+            }
+            _ => None,
+        };
+        match c {
+            Some(c) if self.text.len() < MAX_REPLY_LEN && fits_prefix(&self.text, c) => {
+                self.text.push(c);
+                self.held.push(key);
+                ReadKey::Held
+            }
+            _ => {
+                let mut keys = self.take_held();
+                keys.push(key);
+                ReadKey::Keys(keys)
+            }
+        }
+    }
+
+    /// The held keys once the rest of their reply is overdue at `now`, to
+    /// handle as typed
+    pub fn take_overdue(&mut self, now: Instant) -> Vec<KeyEvent> {
+        match self.held_since {
+            Some(since) if now.saturating_duration_since(since) >= REPLY_TIMEOUT => {
+                self.take_held()
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    fn take_held(&mut self) -> Vec<KeyEvent> {
+        let held = std::mem::take(&mut self.held);
+        self.clear();
+        held
+    }
+
+    fn clear(&mut self) {
+        self.held.clear();
+        self.held_since = None;
+        self.text.clear();
+    }
+}
+
+/// Whether `text` followed by `c` can still start `11;`
+fn fits_prefix(text: &str, c: char) -> bool {
+    const PREFIX: &[u8] = b"11;";
+    text.len() >= PREFIX.len() || PREFIX.get(text.len()) == Some(&(c as u8))
+}
+
+/// Read the color of an OSC 11 reply after its `11;`, such as
+/// `rgb:ffff/ffff/ffff`, with 1 to 4 hex digits a channel
+pub fn parse_background_reply(reply: &str) -> Option<Appearance> {
+    let channels = reply
+        .strip_prefix("rgb:")
+        .or_else(|| reply.strip_prefix("rgba:"))?;
+    let mut channels = channels.split('/').map(|channel| {
+        if channel.is_empty() || channel.len() > 4 {
+            return None;
+        }
+        let value = u32::from_str_radix(channel, 16).ok()?;
+        let max = (1u32 << (4 * channel.len())) - 1;
+        Some((value * 255 / max) as u8)
+    });
+    let r = channels.next()??;
+    let g = channels.next()??;
+    let b = channels.next()??;
+    Appearance::of_background(Color::Rgb(r, g, b))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(c: char, modifiers: KeyModifiers) -> KeyEvent {
+        KeyEvent::new(KeyCode::Char(c), modifiers)
+    }
+
+    /// The keys crossterm reads for a reply ending in BEL
+    fn reply_keys(reply: &str) -> Vec<KeyEvent> {
+        let mut keys = vec![key(']', KeyModifiers::ALT)];
+        for c in reply.chars() {
+            let modifiers = if c.is_ascii_uppercase() {
+                KeyModifiers::SHIFT
+            } else {
+                KeyModifiers::NONE
+            };
+            keys.push(key(c, modifiers));
+        }
+        keys.push(key('g', KeyModifiers::CONTROL));
+        keys
+    }
+
+    fn read_all(reader: &mut ReplyReader, keys: Vec<KeyEvent>, now: Instant) -> Vec<ReadKey> {
+        keys.into_iter()
+            .map(|key| reader.read_key(key, now))
+            .filter(|read| *read != ReadKey::Held)
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_background_reply() {
+        assert_eq!(
+            parse_background_reply("rgb:ffff/ffff/ffff"),
+            Some(Appearance::Light)
+        );
+        assert_eq!(
+            parse_background_reply("rgb:1e1e/1e1e/1e1e"),
+            Some(Appearance::Dark)
+        );
+        assert_eq!(
+            parse_background_reply("rgba:FD/F6/E3/FF"),
+            Some(Appearance::Light)
+        );
+        assert_eq!(parse_background_reply("rgb:0/0/0"), Some(Appearance::Dark));
+        assert_eq!(parse_background_reply("rgb:ffff/ffff"), None);
+        assert_eq!(parse_background_reply("rgb:fffff/0/0"), None);
+        assert_eq!(parse_background_reply("#ffffff"), None);
+    }
+
+    #[test]
+    fn test_reply_to_a_query_is_read() {
+        let now = Instant::now();
+        let mut reader = ReplyReader::default();
+        reader.expect_reply(now);
+        assert_eq!(
+            read_all(&mut reader, reply_keys("11;rgb:FFFF/FFFF/FFFF"), now),
+            vec![ReadKey::Reply(Some(Appearance::Light))]
+        );
+        assert!(!reader.is_awaiting(now));
+
+        // Ended by ST instead of BEL
+        reader.expect_reply(now);
+        let mut keys = reply_keys("11;rgb:0000/0000/0000");
+        keys.pop();
+        keys.push(key('\\', KeyModifiers::ALT));
+        assert_eq!(
+            read_all(&mut reader, keys, now),
+            vec![ReadKey::Reply(Some(Appearance::Dark))]
+        );
+    }
+
+    #[test]
+    fn test_keys_without_a_query_are_typed() {
+        let now = Instant::now();
+        let mut reader = ReplyReader::default();
+        let keys = reply_keys("11;rgb:ffff/ffff/ffff");
+        let read = read_all(&mut reader, keys.clone(), now);
+        assert_eq!(read.len(), keys.len());
+        assert_eq!(read[0], ReadKey::Keys(vec![keys[0]]));
+
+        // Nor once the query timed out
+        reader.expect_reply(now);
+        let later = now + QUERY_TIMEOUT;
+        assert_eq!(
+            reader.read_key(keys[0], later),
+            ReadKey::Keys(vec![keys[0]])
+        );
+    }
+
+    #[test]
+    fn test_keys_that_stop_matching_are_given_back() {
+        let now = Instant::now();
+        let mut reader = ReplyReader::default();
+        reader.expect_reply(now);
+        let alt_bracket = key(']', KeyModifiers::ALT);
+        let x = key('x', KeyModifiers::NONE);
+        assert_eq!(reader.read_key(alt_bracket, now), ReadKey::Held);
+        assert_eq!(reader.read_key(x, now), ReadKey::Keys(vec![alt_bracket, x]));
+
+        // The query still takes its reply
+        assert_eq!(
+            read_all(&mut reader, reply_keys("11;rgb:f/f/f"), now),
+            vec![ReadKey::Reply(Some(Appearance::Light))]
+        );
+    }
+
+    #[test]
+    fn test_overdue_reply_is_given_back() {
+        let now = Instant::now();
+        let mut reader = ReplyReader::default();
+        reader.expect_reply(now);
+        let alt_bracket = key(']', KeyModifiers::ALT);
+        assert_eq!(reader.read_key(alt_bracket, now), ReadKey::Held);
+        assert!(reader.take_overdue(now).is_empty());
+        assert_eq!(reader.take_overdue(now + REPLY_TIMEOUT), vec![alt_bracket]);
+        assert!(reader.take_overdue(now + REPLY_TIMEOUT).is_empty());
+    }
+
+    #[test]
+    fn test_unreadable_reply_is_taken_without_a_color() {
+        let now = Instant::now();
+        let mut reader = ReplyReader::default();
+        reader.expect_reply(now);
+        assert_eq!(
+            read_all(&mut reader, reply_keys("11;?"), now),
+            vec![ReadKey::Reply(None)]
+        );
+    }
+}
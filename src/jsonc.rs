@@ -0,0 +1,774 @@
+//! JSON with comments and trailing commas (JSONC)
+//!
+//! Fresh's config files and many tool configs (tsconfig.json, VS Code
+//! settings) allow `//` and `/* */` comments and a trailing comma before `]`
+//! or `}`. [`from_str`] reads such text with serde_json by blanking those out
+//! first, which keeps every byte offset, so error line and column numbers
+//! still point into the original text. [`update`] rewrites a document to a
+//! new value, editing only the members that changed so the comments around
+//! them survive.
+
+use serde::de::DeserializeOwned;
+use serde_json::{Map, Value};
+
+/// Deserialize JSONC text
+pub fn from_str<T: DeserializeOwned>(text: &str) -> serde_json::Result<T> {
+    serde_json::from_str(&to_strict_json(text))
+}
+
+/// `text` with its comments replaced by spaces
+///
+/// Newlines inside block comments are kept. An unterminated block comment is
+/// left alone, so parsing the result fails where it starts.
+pub fn strip_comments(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut out = bytes.to_vec();
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'"' => i = skip_string(bytes, i),
+            b'/' if bytes.get(i + 1) == Some(&b'/') => {
+                let end = line_end(bytes, i);
+                out[i..end].fill(b' ');
+                i = end;
+            }
+            b'/' if bytes.get(i + 1) == Some(&b'*') => {
+                let Some(close) = find(bytes, i + 2, b"*/") else {
+                    break;
+                };
+                let end = close + 2;
+                for byte in &mut out[i..end] {
+                    if !matches!(*byte, b'\n' | b'\r') {
+                        *byte = b' ';
+                    }
+                }
+                i = end;
+            }
+            _ => i += 1,
+        }
+    }
+    // Only ASCII bytes were touched and whole comments were blanked, so any
+    // multi-byte character was either kept or replaced entirely
+    String::from_utf8(out).expect("blanking comments keeps UTF-8 valid")
+}
+
+/// `text` with its comments and trailing commas replaced by spaces, which
+/// serde_json accepts if the rest is valid JSON
+pub fn to_strict_json(text: &str) -> String {
+    let mut out = strip_comments(text).into_bytes();
+    // A comma only trails if a value comes before it
+    let mut after_value = false;
+    let mut i = 0;
+    while i < out.len() {
+        match out[i] {
+            b'"' => {
+                i = skip_string(&out, i);
+                after_value = true;
+                continue;
+            }
+            b',' => {
+                let next = out[i + 1..].iter().find(|byte| !byte.is_ascii_whitespace());
+                if after_value && matches!(next, Some(b']' | b'}')) {
+                    out[i] = b' ';
+                }
+                after_value = false;
+            }
+            b'[' | b'{' | b':' => after_value = false,
+            byte if !byte.is_ascii_whitespace() => after_value = true,
+            _ => {}
+        }
+        i += 1;
+    }
+    String::from_utf8(out).expect("blanking commas keeps UTF-8 valid")
+}
+
+/// Whether `text` uses comments or trailing commas, which a plain JSON
+/// writer would drop
+pub fn has_jsonc_syntax(text: &str) -> bool {
+    to_strict_json(text) != text
+}
+
+/// `original` rewritten so that it parses to `new_value`, keeping its
+/// comments and layout wherever the value didn't change
+///
+/// Changed members get their new value in place, removed members are cut
+/// out with their line, and new members are added after the last one, in the
+/// object's indentation. Returns None if `original` can't be parsed, if
+/// either side isn't an object, or if the edited text wouldn't parse back to
+/// `new_value`.
+pub fn update(original: &str, new_value: &Value) -> Option<String> {
+    let stripped = strip_comments(original);
+    let root = Parser::new(stripped.as_bytes()).document()?;
+    let (Some(_), Value::Object(new_map)) = (&root.members, new_value) else {
+        return None;
+    };
+
+    let updater = Updater {
+        original,
+        stripped: &stripped,
+        unit: indent_unit(original, &root),
+        newline: if original.contains("\r\n") {
+            "\r\n"
+        } else {
+            "\n"
+        }
+        .to_string(),
+    };
+    let mut edits = Vec::new();
+    updater.update_object(&root, new_map, &mut edits)?;
+
+    // Apply from the end so earlier offsets stay valid; of two edits at the
+    // same offset the removal goes first, so an insertion lands before the
+    // text that follows it
+    edits.sort_by_key(|edit| std::cmp::Reverse((edit.start, edit.end)));
+    let mut text = original.to_string();
+    for edit in edits {
+        text.replace_range(edit.start..edit.end, &edit.text);
+    }
+
+    let parsed: Value = from_str(&text).ok()?;
+    (parsed == *new_value).then_some(text)
+}
+
+/// Index just past the string starting at the `"` at `start`
+fn skip_string(bytes: &[u8], start: usize) -> usize {
+    let mut i = start + 1;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => i += 2,
+            b'"' => return i + 1,
+            _ => i += 1,
+        }
+    }
+    bytes.len()
+}
+
+/// Index of the newline ending the line `i` is on, or the end of the text
+fn line_end(bytes: &[u8], i: usize) -> usize {
+    bytes[i..]
+        .iter()
+        .position(|&byte| byte == b'\n')
+        .map_or(bytes.len(), |offset| i + offset)
+}
+
+/// Index of the start of the line `i` is on
+fn line_start(bytes: &[u8], i: usize) -> usize {
+    bytes[..i]
+        .iter()
+        .rposition(|&byte| byte == b'\n')
+        .map_or(0, |offset| offset + 1)
+}
+
+fn find(bytes: &[u8], from: usize, needle: &[u8]) -> Option<usize> {
+    bytes
+        .get(from..)?
+        .windows(needle.len())
+        .position(|window| window == needle)
+        .map(|offset| from + offset)
+}
+
+/// A value in the text, with the members of an object
+struct Node {
+    start: usize,
+    end: usize,
+    members: Option<Vec<Member>>,
+}
+
+struct Member {
+    key: String,
+    key_start: usize,
+    value: Node,
+    /// Offset of the comma after the value, if there is one
+    comma: Option<usize>,
+}
+
+/// Finds where values are in JSONC text whose comments were stripped
+struct Parser<'a> {
+    text: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn new(text: &'a [u8]) -> Self {
+        Self { text, pos: 0 }
+    }
+
+    fn document(mut self) -> Option<Node> {
+        let node = self.value()?;
+        self.skip_whitespace();
+        (self.pos == self.text.len()).then_some(node)
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self
+            .text
+            .get(self.pos)
+            .is_some_and(|byte| byte.is_ascii_whitespace())
+        {
+            self.pos += 1;
+        }
+    }
+
+    fn value(&mut self) -> Option<Node> {
+        self.skip_whitespace();
+        let start = self.pos;
+        let members =
+            match *self.text.get(self.pos)? {
+                b'{' => Some(self.object_members()?),
+                b'[' => {
+                    self.array()?;
+                    None
+                }
+                b'"' => {
+                    self.string()?;
+                    None
+                }
+                _ => {
+                    while self.text.get(self.pos).is_some_and(|&byte| {
+                        !byte.is_ascii_whitespace() && !b",:]}".contains(&byte)
+                    }) {
+                        self.pos += 1;
+                    }
+                    if self.pos == start {
+                        return None;
+                    }
+                    None
+                }
+            };
+        Some(Node {
+            start,
+            end: self.pos,
+            members,
+        })
+    }
+
+    fn string(&mut self) -> Option<()> {
+        let end = skip_string(self.text, self.pos);
+        if self.text.get(end - 1) != Some(&b'"') || end == self.pos + 1 {
+            return None;
+        }
+        self.pos = end;
+        Some(())
+    }
+
+    fn array(&mut self) -> Option<()> {
+        self.pos += 1;
+        self.skip_whitespace();
+        if self.text.get(self.pos) == Some(&b']') {
+            self.pos += 1;
+            return Some(());
+        }
+        loop {
+            self.value()?;
+            self.skip_whitespace();
+            match *self.text.get(self.pos)? {
+                b',' => {
+                    self.pos += 1;
+                    self.skip_whitespace();
+                    if self.text.get(self.pos) == Some(&b']') {
+                        self.pos += 1;
+                        return Some(());
+                    }
+                }
+                b']' => {
+                    self.pos += 1;
+                    return Some(());
+                }
+                _ => return None,
+            }
+        }
+    }
+
+    fn object_members(&mut self) -> Option<Vec<Member>> {
+        self.pos += 1;
+        let mut members = Vec::new();
+        self.skip_whitespace();
+        if self.text.get(self.pos) == Some(&b'}') {
+            self.pos += 1;
+            return Some(members);
+        }
+        loop {
+            self.skip_whitespace();
+            let key_start = self.pos;
+            if self.text.get(self.pos) != Some(&b'"') {
+                return None;
+            }
+            self.string()?;
+            let key_text = std::str::from_utf8(&self.text[key_start..self.pos]).ok()?;
+            let key: String = serde_json::from_str(key_text).ok()?;
+            self.skip_whitespace();
+            if self.text.get(self.pos) != Some(&b':') {
+                return None;
+            }
+            self.pos += 1;
+            let value = self.value()?;
+            self.skip_whitespace();
+            let comma = (self.text.get(self.pos) == Some(&b',')).then_some(self.pos);
+            members.push(Member {
+                key,
+                key_start,
+                value,
+                comma,
+            });
+            match *self.text.get(self.pos)? {
+                b',' => {
+                    self.pos += 1;
+                    self.skip_whitespace();
+                    if self.text.get(self.pos) == Some(&b'}') {
+                        self.pos += 1;
+                        return Some(members);
+                    }
+                }
+                b'}' => {
+                    self.pos += 1;
+                    return Some(members);
+                }
+                _ => return None,
+            }
+        }
+    }
+}
+
+/// A replacement of `start..end` in the original text
+struct Edit {
+    start: usize,
+    end: usize,
+    text: String,
+}
+
+struct Updater<'a> {
+    original: &'a str,
+    /// `original` with its comments blanked out
+    stripped: &'a str,
+    /// One level of indentation, as the file uses it
+    unit: String,
+    /// The file's line ending
+    newline: String,
+}
+
+impl Updater<'_> {
+    fn update_object(
+        &self,
+        object: &Node,
+        new_map: &Map<String, Value>,
+        edits: &mut Vec<Edit>,
+    ) -> Option<()> {
+        let members = object.members.as_ref()?;
+        let mut kept = Vec::new();
+        for member in members {
+            match new_map.get(&member.key) {
+                Some(new_value) => {
+                    kept.push(member);
+                    self.update_member(member, new_value, edits)?;
+                }
+                None => edits.push(self.removal(member)),
+            }
+        }
+
+        let added: Vec<(&String, &Value)> = new_map
+            .iter()
+            .filter(|(key, _)| !members.iter().any(|member| &member.key == *key))
+            .collect();
+        let trailing_comma = members.last().is_some_and(|member| member.comma.is_some());
+        let last_kept = kept.last().copied();
+
+        if added.is_empty() {
+            // Dropping the last members leaves a comma after the new last
+            // one, which a file without trailing commas shouldn't get
+            if let Some(last) = last_kept {
+                let dropped_after = members.last().map(|m| m.key_start) != Some(last.key_start);
+                if let (true, false, Some(comma)) = (dropped_after, trailing_comma, last.comma) {
+                    edits.push(Edit {
+                        start: comma,
+                        end: comma + 1,
+                        text: String::new(),
+                    });
+                }
+            }
+            return Some(());
+        }
+
+        let indent = match last_kept.or(members.first()) {
+            Some(member) if self.starts_line(member.key_start) => {
+                self.line_indent(member.key_start).to_string()
+            }
+            _ => format!("{}{}", self.line_indent(object.start), self.unit),
+        };
+        let rendered: Vec<String> = added
+            .iter()
+            .map(|(key, value)| {
+                format!(
+                    "{}{}: {}",
+                    indent,
+                    serde_json::to_string(key).unwrap_or_default(),
+                    self.render(value, &indent)
+                )
+            })
+            .collect();
+
+        match last_kept {
+            Some(last) => {
+                // The new members go after the comments on the last line,
+                // and the last member needs a comma before them
+                let after_value = last.value.end;
+                let (at, mut text) = match last.comma {
+                    Some(comma) => (self.end_of_trailing(comma + 1), String::new()),
+                    None => {
+                        let at = self.end_of_trailing(after_value);
+                        if at == after_value {
+                            (at, ",".to_string())
+                        } else {
+                            edits.push(Edit {
+                                start: after_value,
+                                end: after_value,
+                                text: ",".to_string(),
+                            });
+                            (at, String::new())
+                        }
+                    }
+                };
+                for (i, member) in rendered.iter().enumerate() {
+                    text.push_str(&self.newline);
+                    text.push_str(member);
+                    if i + 1 < rendered.len() || (trailing_comma && last.comma.is_some()) {
+                        text.push(',');
+                    }
+                }
+                edits.push(Edit {
+                    start: at,
+                    end: at,
+                    text,
+                });
+            }
+            None => {
+                let close = object.end - 1;
+                let bytes = self.original.as_bytes();
+                let close_line = line_start(bytes, close);
+                let own_line =
+                    close_line > object.start && self.original[close_line..close].trim().is_empty();
+                let body = rendered.join(&format!(",{}", self.newline));
+                if own_line {
+                    edits.push(Edit {
+                        start: close_line,
+                        end: close_line,
+                        text: format!("{}{}", body, self.newline),
+                    });
+                } else {
+                    edits.push(Edit {
+                        start: close,
+                        end: close,
+                        text: format!(
+                            "{nl}{}{nl}{}",
+                            body,
+                            self.line_indent(object.start),
+                            nl = self.newline
+                        ),
+                    });
+                }
+            }
+        }
+        Some(())
+    }
+
+    fn update_member(
+        &self,
+        member: &Member,
+        new_value: &Value,
+        edits: &mut Vec<Edit>,
+    ) -> Option<()> {
+        let node = &member.value;
+        let old_value: Value = from_str(&self.stripped[node.start..node.end]).ok()?;
+        if old_value == *new_value {
+            return Some(());
+        }
+        if let (Some(_), Value::Object(new_map)) = (&node.members, new_value) {
+            return self.update_object(node, new_map, edits);
+        }
+        edits.push(Edit {
+            start: node.start,
+            end: node.end,
+            text: self.render(new_value, self.line_indent(member.key_start)),
+        });
+        Some(())
+    }
+
+    /// The edit cutting `member` out, with its line when it has one to itself
+    fn removal(&self, member: &Member) -> Edit {
+        let bytes = self.original.as_bytes();
+        let end = member.comma.map_or(member.value.end, |comma| comma + 1);
+        if self.starts_line(member.key_start) {
+            let trailing = self.end_of_trailing(end);
+            let line_break = match &bytes[trailing..] {
+                [] => Some(0),
+                [b'\n', ..] => Some(1),
+                [b'\r', b'\n', ..] => Some(2),
+                _ => None,
+            };
+            if let Some(len) = line_break {
+                return Edit {
+                    start: line_start(bytes, member.key_start),
+                    end: trailing + len,
+                    text: String::new(),
+                };
+            }
+        }
+        Edit {
+            start: member.key_start,
+            end,
+            text: String::new(),
+        }
+    }
+
+    /// Pretty-printed `value`, continuation lines indented by `indent`
+    fn render(&self, value: &Value, indent: &str) -> String {
+        let mut out = Vec::new();
+        let formatter = serde_json::ser::PrettyFormatter::with_indent(self.unit.as_bytes());
+        let mut serializer = serde_json::Serializer::with_formatter(&mut out, formatter);
+        if serde::Serialize::serialize(value, &mut serializer).is_err() {
+            return value.to_string();
+        }
+        String::from_utf8_lossy(&out).replace('\n', &format!("{}{}", self.newline, indent))
+    }
+
+    /// Whether only whitespace precedes `i` on its line
+    fn starts_line(&self, i: usize) -> bool {
+        let start = line_start(self.original.as_bytes(), i);
+        self.original[start..i].trim().is_empty()
+    }
+
+    /// The whitespace at the start of the line `i` is on
+    fn line_indent(&self, i: usize) -> &str {
+        let start = line_start(self.original.as_bytes(), i);
+        let line = &self.original[start..];
+        &line[..line.len() - line.trim_start_matches([' ', '\t']).len()]
+    }
+
+    /// Index past the spaces and same-line comments following `i`, which
+    /// belong to whatever ends at `i`
+    fn end_of_trailing(&self, mut i: usize) -> usize {
+        let bytes = self.original.as_bytes();
+        loop {
+            while matches!(bytes.get(i), Some(b' ' | b'\t')) {
+                i += 1;
+            }
+            if bytes[i..].starts_with(b"//") {
+                let end = line_end(bytes, i);
+                return if bytes[end - 1] == b'\r' {
+                    end - 1
+                } else {
+                    end
+                };
+            }
+            if bytes[i..].starts_with(b"/*") {
+                match find(bytes, i + 2, b"*/") {
+                    Some(close) if !bytes[i..close].contains(&b'\n') => i = close + 2,
+                    _ => return i,
+                }
+            } else {
+                return i;
+            }
+        }
+    }
+}
+
+/// The indentation of the root object's first member, or two spaces
+fn indent_unit(original: &str, root: &Node) -> String {
+    let bytes = original.as_bytes();
+    root.members
+        .as_ref()
+        .and_then(|members| members.first())
+        .map(|member| {
+            let start = line_start(bytes, member.key_start);
+            original[start..member.key_start].to_string()
+        })
+        .filter(|indent| !indent.is_empty() && indent.trim().is_empty())
+        .unwrap_or_else(|| "  ".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn parse(text: &str) -> Value {
+        from_str(text).unwrap()
+    }
+
+    #[test]
+    fn test_line_and_block_comments() {
+        let text = r#"// leading
+{
+  /* before key */ "a" /* between key and colon */ : /* before value */ 1, // after
+  "b": [1, /* inside array */ 2] /* before close */
+} // at the end"#;
+        assert_eq!(parse(text), json!({"a": 1, "b": [1, 2]}));
+    }
+
+    #[test]
+    fn test_comment_markers_in_strings_are_kept() {
+        let text = r#"{"url": "http://example.com/*x*/", "quote": "\"//\""} // done"#;
+        assert_eq!(
+            parse(text),
+            json!({"url": "http://example.com/*x*/", "quote": "\"//\""})
+        );
+    }
+
+    #[test]
+    fn test_comment_at_end_without_newline() {
+        assert_eq!(parse("{\"a\": 1}\n// trailing"), json!({"a": 1}));
+        assert_eq!(parse("{\"a\": 1}/* trailing */"), json!({"a": 1}));
+    }
+
+    #[test]
+    fn test_comment_between_tokens_without_spaces() {
+        assert_eq!(
+            parse(
+                r#"{"a":/**/1/**/,"b"://x
+2}"#
+            ),
+            json!({"a": 1, "b": 2})
+        );
+    }
+
+    #[test]
+    fn test_block_comment_keeps_line_numbers() {
+        let text = "{\n/* one\ntwo\nthree */\n\"a\": }";
+        let error = from_str::<Value>(text).unwrap_err();
+        assert_eq!(error.line(), 5);
+    }
+
+    #[test]
+    fn test_unterminated_block_comment_is_an_error() {
+        assert!(from_str::<Value>("{\"a\": 1} /* open").is_err());
+        assert!(from_str::<Value>("{\"a\": /* open 1}").is_err());
+    }
+
+    #[test]
+    fn test_non_ascii_comments() {
+        assert_eq!(
+            parse("{\"a\": 1 /* ünïcödé ✓ */} // 日本語"),
+            json!({"a": 1})
+        );
+    }
+
+    #[test]
+    fn test_trailing_commas_in_nested_arrays() {
+        assert_eq!(parse("[1, [2,], [[3,],],]"), json!([1, [2], [[3]]]));
+        assert_eq!(
+            parse("{\"a\": [1, {\"b\": [2,],},], // x\n}"),
+            json!({"a": [1, {"b": [2]}]})
+        );
+    }
+
+    #[test]
+    fn test_trailing_comma_before_comment() {
+        assert_eq!(parse("[1, // one\n /* end */ ]"), json!([1]));
+    }
+
+    #[test]
+    fn test_commas_in_strings_are_kept() {
+        assert_eq!(parse(r#"["a,]", "b,}",]"#), json!(["a,]", "b,}"]));
+    }
+
+    #[test]
+    fn test_missing_values_are_still_errors() {
+        assert!(from_str::<Value>("[1,,]").is_err());
+        assert!(from_str::<Value>("{,}").is_err());
+    }
+
+    #[test]
+    fn test_has_jsonc_syntax() {
+        assert!(!has_jsonc_syntax(r#"{"a": "//"}"#));
+        assert!(has_jsonc_syntax("{\"a\": 1} // x"));
+        assert!(has_jsonc_syntax("{\"a\": 1,}"));
+    }
+
+    #[test]
+    fn test_update_changes_value_and_keeps_comments() {
+        let original = r#"{
+  // Editor settings
+  "editor": {
+    "tab_size": 4, // my favourite
+    "line_numbers": true
+  },
+  /* Theme */
+  "theme": "dark"
+}
+"#;
+        let updated = update(
+            original,
+            &json!({"editor": {"tab_size": 2, "line_numbers": true}, "theme": "dark"}),
+        )
+        .unwrap();
+        assert_eq!(
+            updated,
+            original.replace("\"tab_size\": 4", "\"tab_size\": 2")
+        );
+    }
+
+    #[test]
+    fn test_update_adds_members() {
+        let original = "{\n    // Theme\n    \"theme\": \"dark\" // dark is best\n}\n";
+        let updated = update(
+            original,
+            &json!({"theme": "dark", "editor": {"tab_size": 2}}),
+        )
+        .unwrap();
+        assert_eq!(
+            updated,
+            "{\n    // Theme\n    \"theme\": \"dark\", // dark is best\n    \"editor\": {\n        \"tab_size\": 2\n    }\n}\n"
+        );
+    }
+
+    #[test]
+    fn test_update_adds_members_keeping_trailing_comma() {
+        let original = "{\n  \"a\": 1,\n}";
+        let updated = update(original, &json!({"a": 1, "b": 2})).unwrap();
+        assert_eq!(updated, "{\n  \"a\": 1,\n  \"b\": 2,\n}");
+    }
+
+    #[test]
+    fn test_update_adds_to_empty_object() {
+        let original = "// nothing yet\n{}\n";
+        let updated = update(original, &json!({"a": 1})).unwrap();
+        assert_eq!(updated, "// nothing yet\n{\n  \"a\": 1\n}\n");
+
+        let original = "{\n  // nothing yet\n}";
+        let updated = update(original, &json!({"a": 1})).unwrap();
+        assert_eq!(updated, "{\n  // nothing yet\n  \"a\": 1\n}");
+    }
+
+    #[test]
+    fn test_update_removes_members() {
+        let original = "{\n  // keep\n  \"a\": 1,\n  \"b\": 2, // about b\n  \"c\": 3\n}";
+        let updated = update(original, &json!({"a": 1, "c": 3})).unwrap();
+        assert_eq!(updated, "{\n  // keep\n  \"a\": 1,\n  \"c\": 3\n}");
+
+        let updated = update(original, &json!({"a": 1, "b": 2})).unwrap();
+        assert_eq!(
+            updated,
+            "{\n  // keep\n  \"a\": 1,\n  \"b\": 2 // about b\n}"
+        );
+    }
+
+    #[test]
+    fn test_update_replaces_and_adds_together() {
+        let original = "{\n  \"a\": 1, // one\n  \"b\": [1, 2]\n}";
+        let updated = update(original, &json!({"a": 1, "c": true})).unwrap();
+        assert_eq!(parse(&updated), json!({"a": 1, "c": true}));
+        assert!(updated.contains("// one"));
+    }
+
+    #[test]
+    fn test_update_keeps_crlf_line_endings() {
+        let original = "{\r\n  \"a\": 1 // one\r\n}\r\n";
+        let updated = update(original, &json!({"a": 1, "b": 2})).unwrap();
+        assert_eq!(updated, "{\r\n  \"a\": 1, // one\r\n  \"b\": 2\r\n}\r\n");
+    }
+
+    #[test]
+    fn test_update_rejects_unparsable_text() {
+        assert_eq!(update("{\"a\": }", &json!({"a": 1})), None);
+        assert_eq!(update("[1]", &json!({"a": 1})), None);
+    }
+}
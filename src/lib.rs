@@ -10,11 +10,14 @@ pub mod v8_init;
 
 // Core types and config are always available (needed for schema generation)
 pub mod config;
+pub mod jsonc;
 pub mod partial_config;
 pub mod types;
 
 // Runtime-only modules (require the "runtime" feature)
 #[cfg(feature = "runtime")]
+pub mod config_dump;
+#[cfg(feature = "runtime")]
 pub mod config_io;
 #[cfg(feature = "runtime")]
 pub mod session;
@@ -1,25 +1,29 @@
 use clap::Parser;
 use crossterm::{
-    cursor::SetCursorStyle,
     event::{
-        poll as event_poll, read as event_read, DisableBracketedPaste, EnableBracketedPaste,
-        Event as CrosstermEvent, KeyEvent, KeyEventKind, KeyboardEnhancementFlags, MouseEvent,
-        PopKeyboardEnhancementFlags, PushKeyboardEnhancementFlags,
+        poll as event_poll, read as event_read, Event as CrosstermEvent, KeyEvent, KeyEventKind,
+        KeyboardEnhancementFlags, MouseEvent,
     },
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
     ExecutableCommand,
 };
+use fresh::app::session::SESSION_WRITE_TIMEOUT;
 #[cfg(target_os = "linux")]
 use fresh::services::gpm::{gpm_to_crossterm, GpmClient};
-use fresh::services::tracing_setup;
+use fresh::services::plugins::manager::PLUGIN_THREAD_NAME;
+use fresh::services::time_source::{ReplayTimeSource, SharedTimeSource};
+use fresh::services::{startup_profile, tracing_setup};
+use fresh::view::terminal_effects::{restore_effects, write_effects, TerminalEffect};
 use fresh::{
-    app::Editor, config, config_io::DirectoryContext, services::release_checker,
-    services::signal_handler, services::warning_log::WarningLogHandle,
+    app::cursor_shape, app::Editor, app::ProjectCarryOver, app::RecordedKeystroke, config,
+    config_io::DirectoryContext,
+    services::release_checker, services::signal_handler, services::warning_log::WarningLogHandle,
 };
 use ratatui::Terminal;
 use std::{
     io::{self, stdout},
-    path::PathBuf,
+    path::{Path, PathBuf},
+    sync::Arc,
     time::Duration,
 };
 
@@ -53,17 +57,203 @@ struct Args {
     #[arg(long, value_name = "LOG_FILE")]
     event_log: Option<PathBuf>,
 
+    /// Replay the keystrokes of a file written with --event-log, timed on a
+    /// virtual clock, before handing the editor over
+    #[arg(long, value_name = "LOG_FILE")]
+    replay: Option<PathBuf>,
+
     /// Don't restore previous session (start fresh)
     #[arg(long)]
     no_session: bool,
 
+    /// Compare the directory trees DIR1 and DIR2 file by file
+    #[arg(long, num_args = 2, value_names = ["DIR1", "DIR2"])]
+    diff_dir: Option<Vec<PathBuf>>,
+
     /// Print the effective configuration as JSON and exit
     #[arg(long)]
     dump_config: bool,
 
+    /// Print only the settings of the config file in use as JSON and exit
+    #[arg(long)]
+    dump_config_user: bool,
+
+    /// Print the resolved keybindings, with the source of each, as JSON and
+    /// exit (all contexts, or only CONTEXT, e.g. "normal")
+    #[arg(long, value_name = "CONTEXT", num_args = 0..=1)]
+    dump_keymap: Option<Option<String>>,
+
+    /// Print the JSON schema of the config file and exit
+    #[arg(long)]
+    dump_schema: bool,
+
     /// Print the directories used by Fresh and exit
     #[arg(long)]
     show_paths: bool,
+
+    /// Write config, themes, grammars, plugins and named layouts to a
+    /// settings archive at PATH and exit
+    #[arg(long, value_name = "PATH", conflicts_with = "import_settings")]
+    export_settings: Option<PathBuf>,
+
+    /// Import the settings archive at PATH, backing up replaced files, and
+    /// exit
+    #[arg(long, value_name = "PATH")]
+    import_settings: Option<PathBuf>,
+
+    /// Comma-separated categories for --export-settings and
+    /// --import-settings: config, themes, grammars, plugins, layouts,
+    /// sessions, history
+    #[arg(long, value_name = "LIST")]
+    settings_categories: Option<String>,
+
+    /// Keep config, state and caches in a `data/` directory next to the
+    /// executable (also enabled by a `fresh.portable` file there)
+    #[arg(long)]
+    portable: bool,
+
+    /// Time each startup phase up to the first frame and print the breakdown
+    /// on exit, or write it to PATH
+    #[arg(long, value_name = "PATH", num_args = 0..=1)]
+    profile_startup: Option<Option<PathBuf>>,
+}
+
+/// Print the JSON asked for by a `--dump-*` flag
+///
+/// Returns `None` if no such flag was given. A config that doesn't load is
+/// reported on stderr and makes the process exit with an error.
+fn handle_dump_flags(args: &Args) -> Option<io::Result<()>> {
+    use fresh::config_dump;
+    use fresh::input::keybindings::KeyContext;
+
+    let wants_config = args.dump_config || args.dump_config_user || args.dump_keymap.is_some();
+    if !wants_config && !args.dump_schema {
+        return None;
+    }
+    fn failed(e: &dyn std::fmt::Display) -> io::Result<()> {
+        eprintln!("Error: {}", e);
+        Err(io::Error::new(io::ErrorKind::InvalidData, e.to_string()))
+    }
+
+    let value = if args.dump_schema {
+        config_dump::schema()
+    } else {
+        let dir_context = match DirectoryContext::from_system(args.portable) {
+            Ok(dir_context) => dir_context,
+            Err(e) => return Some(Err(e)),
+        };
+        let working_dir = std::env::current_dir().unwrap_or_default();
+        let config_path = args.config.as_deref();
+        let config = match config_dump::load_config(&dir_context, &working_dir, config_path) {
+            Ok(config) => config,
+            Err(e) => return Some(failed(&e)),
+        };
+        let value = if args.dump_config_user {
+            config_dump::user_config(&dir_context, &working_dir, config_path)
+        } else if let Some(context) = &args.dump_keymap {
+            let context = match context {
+                Some(name) => match KeyContext::from_when_clause(name) {
+                    Some(context) => Some(context),
+                    None => return Some(failed(&format!("Unknown keybinding context: {}", name))),
+                },
+                None => None,
+            };
+            // Action descriptions follow the configured locale
+            fresh::i18n::init_with_config(config.locale.as_option());
+            Ok(config_dump::keymap(&config, context))
+        } else {
+            config_dump::effective_config(&config)
+        };
+        match value {
+            Ok(value) => value,
+            Err(e) => return Some(failed(&e)),
+        }
+    };
+
+    Some(match serde_json::to_string_pretty(&value) {
+        Ok(json) => {
+            println!("{}", json);
+            Ok(())
+        }
+        Err(e) => failed(&e),
+    })
+}
+
+/// Export or import a settings archive for `--export-settings` or
+/// `--import-settings`
+///
+/// Returns `None` if neither flag was given.
+fn handle_settings_archive_flags(args: &Args) -> Option<io::Result<()>> {
+    use fresh::services::settings_archive::{self, ArchiveCategory, FileChange, SettingsArchive};
+
+    if args.export_settings.is_none() && args.import_settings.is_none() {
+        return None;
+    }
+    fn failed(e: &dyn std::fmt::Display) -> io::Result<()> {
+        eprintln!("Error: {}", e);
+        Err(io::Error::new(io::ErrorKind::InvalidData, e.to_string()))
+    }
+
+    let dir_context = match DirectoryContext::from_system(args.portable) {
+        Ok(dir_context) => dir_context,
+        Err(e) => return Some(Err(e)),
+    };
+    let categories = match args
+        .settings_categories
+        .as_deref()
+        .map(ArchiveCategory::parse_list)
+    {
+        Some(Ok(categories)) => Some(categories),
+        Some(Err(e)) => return Some(failed(&e)),
+        None => None,
+    };
+
+    if let Some(path) = &args.export_settings {
+        let categories = categories.unwrap_or_else(ArchiveCategory::defaults);
+        return Some(
+            match settings_archive::export(&dir_context, &categories, path) {
+                Ok(manifest) => {
+                    println!(
+                        "Exported {} files to {}",
+                        manifest.files.len(),
+                        path.display()
+                    );
+                    Ok(())
+                }
+                Err(e) => failed(&e),
+            },
+        );
+    }
+
+    let path = args.import_settings.as_ref()?;
+    let archive = match SettingsArchive::read(path) {
+        Ok(archive) => archive,
+        Err(e) => return Some(failed(&e)),
+    };
+    // Without a choice, everything the archive holds is imported
+    let categories =
+        categories.unwrap_or_else(|| archive.manifest.categories.iter().copied().collect());
+    let plan = match archive.plan(&dir_context, &categories) {
+        Ok(plan) => plan,
+        Err(e) => return Some(failed(&e)),
+    };
+    let backup_dir = settings_archive::backup_dir(&dir_context);
+    Some(match plan.apply(&backup_dir) {
+        Ok(report) => {
+            println!(
+                "Imported {} files ({} new, {} replaced, {} unchanged)",
+                report.written,
+                plan.count(FileChange::New),
+                plan.count(FileChange::Replace),
+                plan.count(FileChange::Unchanged)
+            );
+            if report.backed_up > 0 {
+                println!("Replaced files were backed up to {}", backup_dir.display());
+            }
+            Ok(())
+        }
+        Err(e) => failed(&e),
+    })
 }
 
 /// Parsed file location from CLI argument in file:line:col format
@@ -78,6 +268,10 @@ struct IterationOutcome {
     loop_result: io::Result<()>,
     update_result: Option<release_checker::ReleaseCheckResult>,
     restart_dir: Option<PathBuf>,
+    /// State for the editor of the next project, when switching projects
+    carry_over: Option<ProjectCarryOver>,
+    /// Binary to run after quitting, installed by a self-update
+    restart_exe: Option<PathBuf>,
 }
 
 struct SetupState {
@@ -241,10 +435,14 @@ fn handle_first_run_setup(
     }
 
     if let Some(handle) = warning_log_handle.take() {
+        handle.set_time_source(editor.time_source().clone());
         editor.set_warning_log(handle.receiver, handle.path);
     }
 
-    if session_enabled {
+    // When another running instance owns the session, the ownership popup
+    // restores it if the user chooses to
+    if editor.claim_session_ownership(session_enabled) && session_enabled {
+        let _phase = startup_profile::phase("session_restore");
         match editor.try_restore_session() {
             Ok(true) => {
                 tracing::info!("Session restored successfully");
@@ -261,29 +459,54 @@ fn handle_first_run_setup(
     // Handle stdin streaming (takes priority over files)
     // Opens with empty/partial buffer, content streams in background
     if let Some(mut stream_state) = stdin_stream.take() {
+        let _phase = startup_profile::phase("stdin");
         tracing::info!("Opening stdin buffer from: {:?}", stream_state.temp_path);
         editor.open_stdin_buffer(&stream_state.temp_path, stream_state.thread_handle.take())?;
     }
 
+    let open_files_phase = startup_profile::phase("open_files");
+    startup_profile::count("files", file_locations.len());
     for loc in file_locations {
-        if loc.path.is_dir() {
+        // A lone directory argument became the working directory above
+        if show_file_explorer {
             continue;
         }
-        editor.open_file(&loc.path)?;
-
-        if let Some(line) = loc.line {
-            editor.goto_line_col(line, loc.column);
+        let _phase = startup_profile::phase_with(|| format!("file {}", loc.path.display()));
+        match editor.open_path(&loc.path) {
+            Ok(Some(_)) => {
+                if let Some(line) = loc.line {
+                    editor.goto_line_col(line, loc.column);
+                }
+            }
+            // Shown in the file explorer, waiting on a prompt, or refused
+            Ok(None) => {}
+            Err(e) => {
+                // An unreachable host shouldn't keep the editor from starting
+                if !fresh::services::file_provider::is_remote_path(&loc.path) {
+                    return Err(e);
+                }
+                tracing::warn!("Failed to open {}: {}", loc.path.display(), e);
+                editor.set_status_message(fresh::i18n::error_opening_file_message(&e.to_string()));
+            }
         }
     }
+    drop(open_files_phase);
+
+    if let Some([left, right]) = args.diff_dir.as_deref() {
+        let current_dir = std::env::current_dir()?;
+        editor.compare_directories(current_dir.join(left), current_dir.join(right));
+    }
 
     if show_file_explorer {
         editor.show_file_explorer();
     }
 
     if editor.has_recovery_files().unwrap_or(false) {
+        let _phase = startup_profile::phase("recovery");
         tracing::info!("Recovery files found from previous session, recovering...");
         match editor.recover_all_buffers() {
             Ok(count) if count > 0 => {
+                startup_profile::count("buffers", count);
                 tracing::info!("Recovered {} buffer(s)", count);
             }
             Ok(_) => {
@@ -295,6 +518,8 @@ fn handle_first_run_setup(
         }
     }
 
+    editor.offer_state_migration();
+
     Ok(())
 }
 
@@ -336,6 +561,12 @@ fn parse_file_location(input: &str) -> FileLocation {
     let search_start = if has_prefix {
         // Find the first colon (the drive letter separator) and skip it
         input.find(':').map(|i| i + 1).unwrap_or(0)
+    } else if fresh::services::file_provider::is_remote_path(Path::new(input)) {
+        // For URLs (e.g., "ssh://host:22/etc/hosts"), skip past the host and port
+        let authority = input.find("://").map_or(0, |i| i + 3);
+        input[authority..]
+            .find('/')
+            .map_or(input.len(), |i| authority + i)
     } else {
         0
     };
@@ -352,11 +583,7 @@ fn parse_file_location(input: &str) -> FileLocation {
         [maybe_col, maybe_line, rest] => {
             if let (Ok(line), Ok(col)) = (maybe_line.parse::<usize>(), maybe_col.parse::<usize>()) {
                 // Both parsed as numbers: file:line:col
-                let path_str = if has_prefix {
-                    format!("{}{}", &input[..search_start], rest)
-                } else {
-                    rest.to_string()
-                };
+                let path_str = format!("{}{}", &input[..search_start], rest);
                 return FileLocation {
                     path: PathBuf::from(path_str),
                     line: Some(line),
@@ -369,11 +596,7 @@ fn parse_file_location(input: &str) -> FileLocation {
         [maybe_line, rest] => {
             if let Ok(line) = maybe_line.parse::<usize>() {
                 // Parsed as number: file:line
-                let path_str = if has_prefix {
-                    format!("{}{}", &input[..search_start], rest)
-                } else {
-                    rest.to_string()
-                };
+                let path_str = format!("{}{}", &input[..search_start], rest);
                 return FileLocation {
                     path: PathBuf::from(path_str),
                     line: Some(line),
@@ -394,6 +617,11 @@ fn parse_file_location(input: &str) -> FileLocation {
 }
 
 fn initialize_app(args: &Args) -> io::Result<SetupState> {
+    let _phase = startup_profile::phase("initialize_app");
+    let logging_phase = startup_profile::phase("logging");
+    let dir_context = DirectoryContext::from_system(args.portable)?;
+    fresh::services::log_dirs::set_log_dir(dir_context.logs_dir());
+
     let log_file = args
         .log_file
         .clone()
@@ -402,6 +630,7 @@ fn initialize_app(args: &Args) -> io::Result<SetupState> {
 
     // Clean up stale log files from dead processes on startup
     fresh::services::log_dirs::cleanup_stale_logs();
+    drop(logging_phase);
 
     tracing::info!("Editor starting");
 
@@ -410,12 +639,19 @@ fn initialize_app(args: &Args) -> io::Result<SetupState> {
 
     let original_hook = std::panic::take_hook();
     std::panic::set_hook(Box::new(move |panic| {
-        let _ = crossterm::execute!(stdout(), crossterm::event::DisableMouseCapture);
-        let _ = stdout().execute(DisableBracketedPaste);
-        let _ = stdout().execute(SetCursorStyle::DefaultUserShape);
-        let _ = stdout().execute(PopKeyboardEnhancementFlags);
+        // The editor keeps running when the plugin thread dies, so leave the
+        // terminal alone
+        if std::thread::current().name() == Some(PLUGIN_THREAD_NAME) {
+            original_hook(panic);
+            return;
+        }
+        let _ = write_effects(
+            &mut stdout(),
+            &restore_effects(cursor_shape::startup_shape()),
+        );
         let _ = disable_raw_mode();
         let _ = stdout().execute(LeaveAlternateScreen);
+        tracing_setup::flush_global();
         original_hook(panic);
     }));
 
@@ -481,6 +717,7 @@ fn initialize_app(args: &Args) -> io::Result<SetupState> {
         .cloned()
         .unwrap_or_else(|| std::env::current_dir().unwrap_or_default());
 
+    let config_phase = startup_profile::phase("config");
     let config = if let Some(config_path) = &args.config {
         match config::Config::load_from_file(config_path) {
             Ok(cfg) => cfg,
@@ -494,19 +731,36 @@ fn initialize_app(args: &Args) -> io::Result<SetupState> {
             }
         }
     } else {
-        config::Config::load_for_working_dir(&effective_working_dir)
+        config::Config::load_for_working_dir(&dir_context, &effective_working_dir)
     };
+    if startup_profile::is_enabled() {
+        let size = serde_json::to_string(&config).map_or(0, |json| json.len());
+        startup_profile::count("json_bytes", size);
+    }
+    drop(config_phase);
 
     // Initialize i18n with the config's locale before creating the editor
     // This ensures menu defaults are created with the correct translations
     fresh::i18n::init_with_config(config.locale.as_option());
 
+    // Start V8 while the terminal is still in normal mode, so a hard abort
+    // leaves a readable message. Other failures are reported by the editor,
+    // which then runs without plugins.
+    #[cfg(feature = "plugins")]
+    if !args.no_plugins {
+        let _phase = startup_profile::phase("v8_init");
+        if let Err(e) = fresh::v8_init::init() {
+            tracing::error!("V8 platform initialization failed: {}", e);
+        }
+    }
+
+    let terminal_phase = startup_profile::phase("terminal_setup");
     enable_raw_mode()?;
     stdout().execute(EnterAlternateScreen)?;
 
     let keyboard_flags = KeyboardEnhancementFlags::DISAMBIGUATE_ESCAPE_CODES
         | KeyboardEnhancementFlags::REPORT_ALTERNATE_KEYS;
-    let _ = stdout().execute(PushKeyboardEnhancementFlags(keyboard_flags));
+    let mut effects = vec![TerminalEffect::PushKeyboardFlags(keyboard_flags)];
     tracing::info!("Enabled keyboard enhancement flags: {:?}", keyboard_flags);
 
     #[cfg(target_os = "linux")]
@@ -521,18 +775,23 @@ fn initialize_app(args: &Args) -> io::Result<SetupState> {
     let gpm_client: Option<()> = None;
 
     if gpm_client.is_none() {
-        let _ = crossterm::execute!(stdout(), crossterm::event::EnableMouseCapture);
+        effects.push(TerminalEffect::MouseCapture(true));
         tracing::info!("Enabled crossterm mouse capture");
     } else {
         tracing::info!("Using GPM for mouse capture, skipping crossterm mouse protocol");
     }
 
     // Enable bracketed paste mode so external pastes arrive as Event::Paste
-    let _ = stdout().execute(EnableBracketedPaste);
+    effects.push(TerminalEffect::BracketedPaste(true));
     tracing::info!("Enabled bracketed paste mode");
 
-    let _ = stdout().execute(config.editor.cursor_style.to_crossterm_style());
-    tracing::info!("Set cursor style to {:?}", config.editor.cursor_style);
+    // Focus reports time the usage statistics count as focused
+    effects.push(TerminalEffect::FocusEvents(true));
+    let _ = write_effects(&mut stdout(), &effects);
+
+    // Terminals can't report their cursor shape, so the one in effect before
+    // the editor starts changing it is the user's default shape
+    cursor_shape::save_startup_shape(config::CursorStyle::Default);
 
     let backend = ratatui::backend::CrosstermBackend::new(stdout());
     let mut terminal = Terminal::new(backend)?;
@@ -540,8 +799,8 @@ fn initialize_app(args: &Args) -> io::Result<SetupState> {
 
     let size = terminal.size()?;
     tracing::info!("Terminal size: {}x{}", size.width, size.height);
+    drop(terminal_phase);
 
-    let dir_context = DirectoryContext::from_system()?;
     let current_working_dir = working_dir;
 
     Ok(SetupState {
@@ -573,14 +832,23 @@ fn run_editor_iteration(
     if let Err(e) = editor.end_recovery_session() {
         tracing::warn!("Failed to end recovery session: {}", e);
     }
+    editor.release_session_ownership();
+    editor.flush_usage_stats();
+
+    // On quit and on restart_dir alike, the servers are done with this root
+    editor.shutdown_lsp_servers();
 
     let update_result = editor.get_update_result().cloned();
     let restart_dir = editor.take_restart_dir();
+    let carry_over = restart_dir.is_some().then(|| editor.take_carry_over());
+    let restart_exe = editor.take_restart_exe();
 
     Ok(IterationOutcome {
         loop_result,
         update_result,
         restart_dir,
+        carry_over,
+        restart_exe,
     })
 }
 
@@ -590,39 +858,29 @@ fn main() -> io::Result<()> {
 
     // Handle --show-paths early (no terminal setup needed)
     if args.show_paths {
-        fresh::services::log_dirs::print_all_paths();
+        let dir_context = DirectoryContext::from_system(args.portable)?;
+        fresh::services::log_dirs::set_log_dir(dir_context.logs_dir());
+        fresh::services::log_dirs::print_all_paths(&dir_context);
         return Ok(());
     }
 
-    // Handle --dump-config early (no terminal setup needed)
-    if args.dump_config {
-        let config = if let Some(config_path) = &args.config {
-            match config::Config::load_from_file(config_path) {
-                Ok(cfg) => cfg,
-                Err(e) => {
-                    eprintln!(
-                        "Error: Failed to load config from {}: {}",
-                        config_path.display(),
-                        e
-                    );
-                    return Err(io::Error::new(io::ErrorKind::InvalidData, e.to_string()));
-                }
-            }
-        } else {
-            config::Config::load_for_working_dir(&std::env::current_dir().unwrap_or_default())
-        };
+    // Handle the --dump-* flags early (no terminal setup needed)
+    if let Some(result) = handle_dump_flags(&args) {
+        return result;
+    }
 
-        // Pretty-print the config as JSON
-        match serde_json::to_string_pretty(&config) {
-            Ok(json) => {
-                println!("{}", json);
-                return Ok(());
-            }
-            Err(e) => {
-                eprintln!("Error: Failed to serialize config: {}", e);
-                return Err(io::Error::new(io::ErrorKind::InvalidData, e.to_string()));
-            }
-        }
+    // Handle --export-settings and --import-settings (no terminal setup needed)
+    if let Some(result) = handle_settings_archive_flags(&args) {
+        return result;
+    }
+
+    if let Some(output) = &args.profile_startup {
+        startup_profile::enable(output.clone());
+    }
+
+    // Put in place an update that couldn't replace the binary last time
+    if let Ok(exe) = std::env::current_exe() {
+        release_checker::finish_pending_update(&exe);
     }
 
     let SetupState {
@@ -650,14 +908,26 @@ fn main() -> io::Result<()> {
     // Track whether we should restore session on restart (for project switching)
     let mut restore_session_on_restart = false;
 
+    // State carried from the editor of the last project to the next one
+    let mut carry_over = None;
+
+    // Keys of --replay are pressed on this clock, which runs in real time after
+    let replay_clock = args
+        .replay
+        .as_ref()
+        .map(|_| Arc::new(ReplayTimeSource::new()));
+
     // Main editor loop - supports restarting with a new working directory
-    // Returns (loop_result, last_update_result) tuple
-    let (result, last_update_result) = loop {
+    // Returns (loop_result, last_update_result, restart_exe) tuple
+    let (result, last_update_result, restart_exe) = loop {
         let first_run = is_first_run;
-        let session_enabled = !args.no_session && file_locations.is_empty();
+        let session_enabled =
+            !args.no_session && file_locations.is_empty() && args.diff_dir.is_none();
 
         // Detect terminal color capability
         let color_capability = fresh::view::color_support::ColorCapability::detect();
+        // A non-UTF-8 locale defaults ui.ascii_only on
+        fresh::view::glyphs::detect_locale();
 
         let mut editor = Editor::with_working_dir(
             config.clone(),
@@ -667,6 +937,10 @@ fn main() -> io::Result<()> {
             dir_context.clone(),
             !args.no_plugins,
             color_capability,
+            replay_clock
+                .clone()
+                .filter(|_| first_run)
+                .map(|clock| clock as SharedTimeSource),
         )?;
 
         #[cfg(target_os = "linux")]
@@ -674,7 +948,12 @@ fn main() -> io::Result<()> {
             editor.set_gpm_active(true);
         }
 
+        if let Some(log) = tracing_setup::global_log() {
+            editor.set_log_file(log.clone());
+        }
+
         if first_run {
+            let _phase = startup_profile::phase("first_run_setup");
             handle_first_run_setup(
                 &mut editor,
                 &args,
@@ -685,7 +964,9 @@ fn main() -> io::Result<()> {
                 session_enabled,
             )?;
         } else {
-            if restore_session_on_restart {
+            if editor.claim_session_ownership(restore_session_on_restart)
+                && restore_session_on_restart
+            {
                 match editor.try_restore_session() {
                     Ok(true) => {
                         tracing::info!("Session restored successfully");
@@ -698,6 +979,9 @@ fn main() -> io::Result<()> {
                     }
                 }
             }
+            if let Some(carry_over) = carry_over.take() {
+                editor.restore_carry_over(carry_over);
+            }
 
             editor.show_file_explorer();
             let path = current_working_dir
@@ -707,10 +991,18 @@ fn main() -> io::Result<()> {
             editor.set_status_message(fresh::i18n::switched_to_project_message(&path));
         }
 
+        editor.record_recent_project();
+
         if let Err(e) = editor.start_recovery_session() {
             tracing::warn!("Failed to start recovery session: {}", e);
         }
 
+        if let (Some(path), Some(clock), true) = (&args.replay, &replay_clock, first_run) {
+            let keys = RecordedKeystroke::read_all(path)?;
+            editor.replay_keystrokes(&keys, |duration| clock.advance(duration))?;
+            clock.go_live();
+        }
+
         let iteration = run_editor_iteration(
             &mut editor,
             session_enabled,
@@ -721,7 +1013,9 @@ fn main() -> io::Result<()> {
 
         let update_result = iteration.update_result;
         let restart_dir = iteration.restart_dir;
+        let restart_exe = iteration.restart_exe;
         let loop_result = iteration.loop_result;
+        carry_over = iteration.carry_over;
 
         drop(editor);
 
@@ -737,17 +1031,26 @@ fn main() -> io::Result<()> {
             continue;
         }
 
-        break (loop_result, update_result);
+        break (loop_result, update_result, restart_exe);
     };
+    tracing_setup::flush_global();
 
     // Clean up terminal
-    let _ = crossterm::execute!(stdout(), crossterm::event::DisableMouseCapture);
-    let _ = stdout().execute(DisableBracketedPaste);
-    let _ = stdout().execute(SetCursorStyle::DefaultUserShape);
-    let _ = stdout().execute(PopKeyboardEnhancementFlags);
+    let _ = write_effects(
+        &mut stdout(),
+        &restore_effects(cursor_shape::startup_shape()),
+    );
     disable_raw_mode()?;
     stdout().execute(LeaveAlternateScreen)?;
 
+    if let Some(report) = startup_profile::take_exit_report() {
+        eprint!("{}", report);
+    }
+
+    if let Some(exe) = restart_exe.filter(|_| result.is_ok()) {
+        return restart_into(&exe);
+    }
+
     // Check for updates after terminal is restored (using cached result)
     if let Some(update_result) = last_update_result {
         if update_result.update_available {
@@ -772,6 +1075,23 @@ fn main() -> io::Result<()> {
     result
 }
 
+/// Replace this process with `exe`, passing the same arguments
+///
+/// Used after a self-update; returns only if `exe` couldn't be started.
+fn restart_into(exe: &Path) -> io::Result<()> {
+    let args: Vec<std::ffi::OsString> = std::env::args_os().skip(1).collect();
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        Err(std::process::Command::new(exe).args(&args).exec())
+    }
+    #[cfg(not(unix))]
+    {
+        let status = std::process::Command::new(exe).args(&args).status()?;
+        std::process::exit(status.code().unwrap_or(0));
+    }
+}
+
 /// Main event loop
 #[cfg(target_os = "linux")]
 fn run_event_loop(
@@ -801,21 +1121,33 @@ fn run_event_loop(
     })
 }
 
-fn run_event_loop_common<F>(
+fn run_event_loop_common<B, F>(
     editor: &mut Editor,
-    terminal: &mut Terminal<ratatui::backend::CrosstermBackend<io::Stdout>>,
+    terminal: &mut Terminal<B>,
     session_enabled: bool,
     mut poll_event: F,
 ) -> io::Result<()>
 where
+    B: ratatui::backend::Backend,
     F: FnMut(Duration) -> io::Result<Option<CrosstermEvent>>,
 {
-    use std::time::Instant;
-
     const FRAME_DURATION: Duration = Duration::from_millis(16); // 60fps
-    let mut last_render = Instant::now();
+                                                                // Fallback size poll for terminals that don't deliver resize events
+    const RESIZE_POLL_INTERVAL: Duration = Duration::from_millis(500);
+    // How soon to check again while plugin hooks wait to be dispatched
+    const HOOK_POLL_INTERVAL: Duration = Duration::from_millis(2);
+    // How long quitting waits for the session save before saying so
+    const SESSION_NOTE_DELAY: Duration = Duration::from_millis(150);
+    // Timers and frame pacing follow the editor's clock
+    let clock = editor.time_source().clone();
+    let mut last_render = clock.now();
+    let mut last_size_check = clock.now();
     let mut needs_render = true;
     let mut pending_event: Option<CrosstermEvent> = None;
+    let mut first_frame_phase = startup_profile::phase("first_frame");
+
+    // Learn the terminal's background for `theme = "auto"`
+    editor.query_terminal_background();
 
     loop {
         // Process async messages and poll for file changes (auto-revert, file tree)
@@ -823,13 +1155,19 @@ where
             needs_render = true;
         }
 
-        // Check mouse hover timer for LSP hover requests
-        if editor.check_mouse_hover_timer() {
+        // Hover, completion, auto-save and the other timers
+        if editor.check_timers() {
+            needs_render = true;
+        }
+
+        // Type keys held for a terminal reply that never completed
+        for key_event in editor.check_terminal_reply_timer() {
+            handle_key_event(editor, key_event)?;
             needs_render = true;
         }
 
-        // Check semantic highlight debounce timer
-        if editor.check_semantic_highlight_timer() {
+        // Ask about project commands held back until the user decides
+        if editor.check_command_trust_requests() {
             needs_render = true;
         }
 
@@ -843,32 +1181,67 @@ where
             needs_render = true;
         }
 
-        if let Err(e) = editor.auto_save_dirty_buffers() {
-            tracing::debug!("Auto-save error: {}", e);
+        // Recover from resizes that never produced a resize event
+        if clock.elapsed_since(last_size_check) >= RESIZE_POLL_INTERVAL {
+            last_size_check = clock.now();
+            if let Ok(size) = terminal.size() {
+                if editor.check_terminal_size(size.width, size.height) {
+                    needs_render = true;
+                }
+            }
         }
 
         if editor.should_quit() {
+            // A copy made right before quitting still reaches the clipboard
+            let _ = write_effects(&mut stdout(), &editor.take_terminal_effects(clock.now()));
             if session_enabled {
-                if let Err(e) = editor.save_session() {
-                    tracing::warn!("Failed to save session: {}", e);
-                } else {
-                    tracing::debug!("Session saved successfully");
+                editor.start_session_save();
+                let mut result = editor.wait_for_session_save(SESSION_NOTE_DELAY);
+                if result.is_none() {
+                    // Say why the editor hasn't closed yet
+                    editor.show_session_saving_note();
+                    terminal.draw(|frame| editor.render(frame))?;
+                    result = editor.wait_for_session_save(SESSION_WRITE_TIMEOUT);
+                }
+                match result {
+                    Some(Ok(_)) => tracing::debug!("Session saved successfully"),
+                    Some(Err(e)) => tracing::warn!("Failed to save session: {}", e),
+                    None => tracing::warn!("Gave up waiting for the session to be saved"),
                 }
             }
             break;
         }
 
-        if needs_render && last_render.elapsed() >= FRAME_DURATION {
+        // Cursor shape, OSC 52 copies and mode toggles go out before the frame
+        let effects = editor.take_terminal_effects(clock.now());
+        let _ = write_effects(&mut stdout(), &effects);
+
+        if needs_render && clock.elapsed_since(last_render) >= FRAME_DURATION {
+            if editor.take_full_redraw_request() {
+                terminal.clear()?;
+            }
             terminal.draw(|frame| editor.render(frame))?;
-            last_render = Instant::now();
+            last_render = clock.now();
             needs_render = false;
+            editor.record_frame_drawn(last_render);
+            if let Some(phase) = first_frame_phase.take() {
+                drop(phase);
+                startup_profile::complete();
+            }
         }
 
         let event = if let Some(e) = pending_event.take() {
             Some(e)
         } else {
-            let timeout = if needs_render {
-                FRAME_DURATION.saturating_sub(last_render.elapsed())
+            let timeout = if editor.has_queued_async_messages() {
+                // Only check for input before handling the next batch
+                Duration::ZERO
+            } else if editor.has_pending_plugin_hooks() {
+                // Hooks go out once the plugins have run the previous ones
+                HOOK_POLL_INTERVAL
+                    .min(FRAME_DURATION.saturating_sub(clock.elapsed_since(last_render)))
+            } else if needs_render {
+                FRAME_DURATION.saturating_sub(clock.elapsed_since(last_render))
             } else {
                 Duration::from_millis(50)
             };
@@ -877,32 +1250,62 @@ where
         };
 
         let Some(event) = event else { continue };
+        let read_at = clock.now();
 
-        let (event, next) = coalesce_mouse_moves(event)?;
+        let (event, repeat, next) = coalesce_mouse_events(event, &mut poll_event)?;
         pending_event = next;
 
-        match event {
+        // Kind of event to record input latency for (events that trigger a redraw)
+        let latency_kind = match event {
             CrosstermEvent::Key(key_event) => {
                 if key_event.kind == KeyEventKind::Press {
-                    handle_key_event(editor, key_event)?;
+                    // A terminal reply comes in as keys
+                    for key_event in editor.sift_terminal_reply(key_event) {
+                        handle_key_event(editor, key_event)?;
+                    }
                     needs_render = true;
+                    Some("key")
+                } else {
+                    None
                 }
             }
             CrosstermEvent::Mouse(mouse_event) => {
-                if handle_mouse_event(editor, mouse_event)? {
+                if handle_mouse_event(editor, mouse_event, repeat)? {
                     needs_render = true;
+                    Some("mouse")
+                } else {
+                    None
                 }
             }
             CrosstermEvent::Resize(w, h) => {
                 editor.resize(w, h);
+                last_size_check = clock.now();
                 needs_render = true;
+                Some("resize")
             }
             CrosstermEvent::Paste(text) => {
                 // External paste from terminal (bracketed paste mode)
                 editor.paste_text(text);
                 needs_render = true;
+                Some("paste")
+            }
+            CrosstermEvent::FocusGained => {
+                editor.handle_focus_change(true);
+                // The OS may have switched the terminal between light and
+                // dark meanwhile
+                editor.query_terminal_background();
+                None
+            }
+            CrosstermEvent::FocusLost => {
+                editor.handle_focus_change(false);
+                None
+            }
+        };
+
+        if let Some(kind) = latency_kind {
+            if editor.input_latency_enabled() {
+                editor.record_input_handled(kind, read_at, clock.now());
             }
-            _ => {}
         }
     }
 
@@ -1010,9 +1413,7 @@ fn handle_key_event(editor: &mut Editor, key_event: KeyEvent) -> io::Result<()>
     );
 
     // Log the keystroke
-    let key_code = format!("{:?}", key_event.code);
-    let modifiers = format!("{:?}", key_event.modifiers);
-    editor.log_keystroke(&key_code, &modifiers);
+    editor.log_keystroke(key_event.code, key_event.modifiers);
 
     // Delegate to the editor's handle_key method
     editor.handle_key(key_event.code, key_event.modifiers)?;
@@ -1020,43 +1421,73 @@ fn handle_key_event(editor: &mut Editor, key_event: KeyEvent) -> io::Result<()>
     Ok(())
 }
 
-/// Handle a mouse event
+/// Handle a mouse event, repeated `repeat` times for coalesced wheel events
 /// Returns true if a re-render is needed
-fn handle_mouse_event(editor: &mut Editor, mouse_event: MouseEvent) -> io::Result<bool> {
+fn handle_mouse_event(
+    editor: &mut Editor,
+    mouse_event: MouseEvent,
+    repeat: u16,
+) -> io::Result<bool> {
     tracing::trace!(
-        "Mouse event received: kind={:?}, column={}, row={}, modifiers={:?}",
+        "Mouse event received: kind={:?}, column={}, row={}, modifiers={:?}, repeat={}",
         mouse_event.kind,
         mouse_event.column,
         mouse_event.row,
-        mouse_event.modifiers
+        mouse_event.modifiers,
+        repeat
     );
 
     // Delegate to the editor's handle_mouse method
-    editor.handle_mouse(mouse_event)
+    editor.handle_mouse_repeated(mouse_event, repeat)
+}
+
+/// Whether a mouse event is a wheel (or trackpad) scroll
+fn is_scroll_event(kind: crossterm::event::MouseEventKind) -> bool {
+    use crossterm::event::MouseEventKind;
+    matches!(
+        kind,
+        MouseEventKind::ScrollUp
+            | MouseEventKind::ScrollDown
+            | MouseEventKind::ScrollLeft
+            | MouseEventKind::ScrollRight
+    )
 }
 
-/// Skip stale mouse move events, return the latest one.
-/// If we read a non-move event while draining, return it as pending.
-fn coalesce_mouse_moves(
+/// Coalesce bursts of mouse events that are already queued.
+///
+/// Stale mouse moves are skipped in favor of the latest one. Consecutive wheel
+/// events in the same direction (trackpads send floods of them) are merged into
+/// one event plus a repeat count, so the burst is handled as a single scroll.
+/// If a different event is read while draining, it is returned as pending.
+fn coalesce_mouse_events<F>(
     event: CrosstermEvent,
-) -> io::Result<(CrosstermEvent, Option<CrosstermEvent>)> {
+    poll_event: &mut F,
+) -> io::Result<(CrosstermEvent, u16, Option<CrosstermEvent>)>
+where
+    F: FnMut(Duration) -> io::Result<Option<CrosstermEvent>>,
+{
     use crossterm::event::MouseEventKind;
 
-    // Only coalesce mouse moves
-    if !matches!(&event, CrosstermEvent::Mouse(m) if m.kind == MouseEventKind::Moved) {
-        return Ok((event, None));
-    }
+    let kind = match &event {
+        CrosstermEvent::Mouse(m) if m.kind == MouseEventKind::Moved || is_scroll_event(m.kind) => {
+            m.kind
+        }
+        _ => return Ok((event, 1, None)),
+    };
 
     let mut latest = event;
-    while event_poll(Duration::ZERO)? {
-        let next = event_read()?;
-        if matches!(&next, CrosstermEvent::Mouse(m) if m.kind == MouseEventKind::Moved) {
-            latest = next; // Newer move, skip the old one
+    let mut repeat: u16 = 1;
+    while let Some(next) = poll_event(Duration::ZERO)? {
+        if matches!(&next, CrosstermEvent::Mouse(m) if m.kind == kind) && repeat < u16::MAX {
+            latest = next; // Newer event of the same kind, fold the old one in
+            if kind != MouseEventKind::Moved {
+                repeat += 1;
+            }
         } else {
-            return Ok((latest, Some(next))); // Hit a click/key, save it
+            return Ok((latest, repeat, Some(next))); // Hit something else, save it
         }
     }
-    Ok((latest, None))
+    Ok((latest, repeat, None))
 }
 
 #[cfg(test)]
@@ -1131,6 +1562,18 @@ mod tests {
         assert_eq!(loc.column, None);
     }
 
+    #[test]
+    fn test_parse_file_location_remote_url() {
+        let loc = parse_file_location("ssh://me@host:2222/etc/hosts");
+        assert_eq!(loc.path, PathBuf::from("ssh://me@host:2222/etc/hosts"));
+        assert_eq!(loc.line, None);
+
+        let loc = parse_file_location("ssh://host:2222/etc/hosts:12:3");
+        assert_eq!(loc.path, PathBuf::from("ssh://host:2222/etc/hosts"));
+        assert_eq!(loc.line, Some(12));
+        assert_eq!(loc.column, Some(3));
+    }
+
     #[test]
     fn test_parse_file_location_line_only_not_col() {
         // "foo:bar:10" -> "10" is col, "bar" isn't line, so no parsing
@@ -1139,6 +1582,259 @@ mod tests {
         assert_eq!(loc.line, None);
         assert_eq!(loc.column, None);
     }
+
+    /// Drive the common event loop with scripted events and check that
+    /// input latency is recorded for each handled keystroke
+    #[test]
+    fn test_event_loop_records_input_latency() {
+        use crossterm::event::{KeyCode, KeyModifiers};
+        use std::collections::VecDeque;
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let mut config = config::Config::default();
+        config.active_keybinding_map = config::KeybindingMapName("default".to_string());
+        config.check_for_updates = false;
+        let mut editor = Editor::for_test(
+            config,
+            80,
+            24,
+            Some(temp_dir.path().to_path_buf()),
+            DirectoryContext::for_testing(temp_dir.path()),
+            fresh::view::color_support::ColorCapability::TrueColor,
+            None,
+            None,
+        )
+        .unwrap();
+        editor.toggle_input_latency_hud();
+
+        let key = |code, modifiers| Some(CrosstermEvent::Key(KeyEvent::new(code, modifiers)));
+        // `None` entries are idle polls, giving the loop a chance to draw a frame
+        let mut events: VecDeque<Option<CrosstermEvent>> = VecDeque::from(vec![
+            key(KeyCode::Down, KeyModifiers::NONE),
+            None,
+            key(KeyCode::Right, KeyModifiers::NONE),
+            key(KeyCode::End, KeyModifiers::NONE),
+            None,
+            key(KeyCode::Char('q'), KeyModifiers::CONTROL),
+        ]);
+
+        let mut terminal = Terminal::new(ratatui::backend::TestBackend::new(80, 24)).unwrap();
+        run_event_loop_common(&mut editor, &mut terminal, false, |timeout| {
+            let event = events.pop_front().expect("event loop did not quit");
+            if event.is_none() {
+                std::thread::sleep(timeout);
+            }
+            Ok(event)
+        })
+        .unwrap();
+
+        // The quit keystroke never gets a frame; the other three do
+        let stats = editor.input_latency_stats().expect("no latency recorded");
+        assert_eq!(stats.samples, 3);
+        assert!(stats.handle.max <= stats.frame.max);
+        assert!(stats.frame.max < Duration::from_secs(5));
+    }
+
+    /// The events crossterm reads for the terminal's reply to an OSC 11
+    /// query for its background color
+    fn background_reply(rgb: &str) -> Vec<Option<CrosstermEvent>> {
+        use crossterm::event::{KeyCode, KeyModifiers};
+
+        let key = |c, modifiers| {
+            Some(CrosstermEvent::Key(KeyEvent::new(
+                KeyCode::Char(c),
+                modifiers,
+            )))
+        };
+        let mut events = vec![key(']', KeyModifiers::ALT)];
+        events.extend(
+            format!("11;rgb:{}", rgb)
+                .chars()
+                .map(|c| key(c, KeyModifiers::NONE)),
+        );
+        events.push(key('g', KeyModifiers::CONTROL));
+        events
+    }
+
+    /// Run the event loop with `config` on `events` (`None` = idle poll) and
+    /// return the name of the theme shown at the end
+    ///
+    /// The loop quits with Ctrl+Q, which only works if no reply was typed
+    /// into the buffer.
+    fn theme_after_events(
+        mut config: config::Config,
+        events: Vec<Option<CrosstermEvent>>,
+    ) -> String {
+        use crossterm::event::{KeyCode, KeyModifiers};
+        use std::collections::VecDeque;
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        config.active_keybinding_map = config::KeybindingMapName("default".to_string());
+        config.check_for_updates = false;
+        let mut editor = Editor::for_test(
+            config,
+            80,
+            24,
+            Some(temp_dir.path().to_path_buf()),
+            DirectoryContext::for_testing(temp_dir.path()),
+            fresh::view::color_support::ColorCapability::TrueColor,
+            None,
+            None,
+        )
+        .unwrap();
+
+        let mut events: VecDeque<Option<CrosstermEvent>> = VecDeque::from(events);
+        events.push_back(None);
+        events.push_back(Some(CrosstermEvent::Key(KeyEvent::new(
+            KeyCode::Char('q'),
+            KeyModifiers::CONTROL,
+        ))));
+        let mut terminal = Terminal::new(ratatui::backend::TestBackend::new(80, 24)).unwrap();
+        run_event_loop_common(&mut editor, &mut terminal, false, |timeout| {
+            let event = events.pop_front().expect("event loop did not quit");
+            if event.is_none() {
+                std::thread::sleep(timeout);
+            }
+            Ok(event)
+        })
+        .unwrap();
+        editor.theme().name.clone()
+    }
+
+    #[test]
+    fn test_auto_theme_follows_terminal_background() {
+        let mut config = config::Config::default();
+        config.theme = config::ThemeName::AUTO.into();
+        config.ui.light_theme = Some("light-high-contrast".into());
+
+        let mut events = vec![None];
+        events.extend(background_reply("ffff/ffff/ffff"));
+        assert_eq!(
+            theme_after_events(config.clone(), events.clone()),
+            "light-high-contrast"
+        );
+
+        // Switching back once the terminal gains focus with a dark background
+        events.push(None);
+        events.push(Some(CrosstermEvent::FocusGained));
+        events.extend(background_reply("1e1e/1e1e/1e1e"));
+        assert_eq!(theme_after_events(config, events), "dark");
+    }
+
+    #[test]
+    fn test_fixed_theme_ignores_terminal_background() {
+        let mut config = config::Config::default();
+        config.theme = "high-contrast".into();
+
+        let mut events = vec![None];
+        events.extend(background_reply("ffff/ffff/ffff"));
+        assert_eq!(theme_after_events(config, events), "high-contrast");
+    }
+
+    fn mouse(kind: crossterm::event::MouseEventKind) -> CrosstermEvent {
+        CrosstermEvent::Mouse(crossterm::event::MouseEvent {
+            kind,
+            column: 40,
+            row: 5,
+            modifiers: crossterm::event::KeyModifiers::NONE,
+        })
+    }
+
+    #[test]
+    fn test_coalesce_horizontal_scroll_burst() {
+        use crossterm::event::MouseEventKind::{ScrollLeft, ScrollRight};
+        use std::collections::VecDeque;
+
+        let mut queued: VecDeque<CrosstermEvent> = VecDeque::from(vec![
+            mouse(ScrollRight),
+            mouse(ScrollRight),
+            mouse(ScrollLeft),
+        ]);
+        let mut poll = |_| Ok(queued.pop_front());
+
+        // Three ScrollRights fold into one; the ScrollLeft is kept for later
+        let (event, repeat, pending) =
+            coalesce_mouse_events(mouse(ScrollRight), &mut poll).unwrap();
+        assert_eq!(event, mouse(ScrollRight));
+        assert_eq!(repeat, 3);
+        assert_eq!(pending, Some(mouse(ScrollLeft)));
+
+        let (event, repeat, pending) = coalesce_mouse_events(mouse(ScrollLeft), &mut poll).unwrap();
+        assert_eq!(event, mouse(ScrollLeft));
+        assert_eq!(repeat, 1);
+        assert_eq!(pending, None);
+    }
+
+    /// Run the event loop on a file with a 100-column line, feeding `events`
+    /// (`None` = idle poll), and return the resulting horizontal scroll
+    fn left_column_after_events(events: Vec<Option<CrosstermEvent>>) -> usize {
+        use crossterm::event::{KeyCode, KeyModifiers};
+        use std::collections::VecDeque;
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let file = temp_dir.path().join("long.txt");
+        std::fs::write(&file, format!("{}\nshort\n", "x".repeat(100))).unwrap();
+
+        let mut config = config::Config::default();
+        config.active_keybinding_map = config::KeybindingMapName("default".to_string());
+        config.check_for_updates = false;
+        config.editor.line_wrap = false;
+        config.mouse.horizontal_scroll_columns = 4;
+        let mut editor = Editor::for_test(
+            config,
+            80,
+            24,
+            Some(temp_dir.path().to_path_buf()),
+            DirectoryContext::for_testing(temp_dir.path()),
+            fresh::view::color_support::ColorCapability::TrueColor,
+            None,
+            None,
+        )
+        .unwrap();
+        editor.open_file(&file).unwrap();
+
+        let mut events: VecDeque<Option<CrosstermEvent>> = VecDeque::from(events);
+        events.push_back(None);
+        events.push_back(Some(CrosstermEvent::Key(KeyEvent::new(
+            KeyCode::Char('q'),
+            KeyModifiers::CONTROL,
+        ))));
+        let mut terminal = Terminal::new(ratatui::backend::TestBackend::new(80, 24)).unwrap();
+        run_event_loop_common(&mut editor, &mut terminal, false, |timeout| {
+            let event = events.pop_front().expect("event loop did not quit");
+            if event.is_none() {
+                std::thread::sleep(timeout);
+            }
+            Ok(event)
+        })
+        .unwrap();
+        editor.active_viewport().left_column
+    }
+
+    #[test]
+    fn test_event_loop_horizontal_scroll_bursts() {
+        use crossterm::event::MouseEventKind::{ScrollLeft, ScrollRight};
+
+        let burst = |kind, count| vec![Some(mouse(kind)); count];
+
+        // A burst of three scrolls moves three steps
+        assert_eq!(left_column_after_events(burst(ScrollRight, 3)), 12);
+
+        // A long burst stops at the end of the longest line, and more scrolling
+        // at the edge doesn't move the view
+        let clamped = left_column_after_events(burst(ScrollRight, 50));
+        assert!(clamped > 12 && clamped < 100, "left_column = {clamped}");
+        let mut events = burst(ScrollRight, 50);
+        events.push(None);
+        events.extend(burst(ScrollRight, 5));
+        assert_eq!(left_column_after_events(events), clamped);
+
+        // Scrolling back stops at the start of the line
+        let mut events = burst(ScrollRight, 50);
+        events.push(None);
+        events.extend(burst(ScrollLeft, 50));
+        assert_eq!(left_column_after_events(events), 0);
+    }
 }
 
 // Property tests use Unix-style path generation strategy, skip on Windows
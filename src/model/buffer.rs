@@ -1,17 +1,20 @@
 /// Text buffer that uses PieceTree with integrated line tracking
 /// Architecture where the tree is the single source of truth for text and line information
+use crate::model::encoding::{self, InvalidBytes, TextEncoding};
 use crate::model::piece_tree::{
     BufferData, BufferLocation, Cursor, PieceInfo, PieceRangeIter, PieceTree, Position,
     StringBuffer, TreeStats,
 };
-use crate::model::piece_tree_diff::PieceTreeDiff;
+use crate::model::piece_tree_diff::{DirtyRegion, PieceTreeDiff};
+use crate::model::position_encoding::PositionEncoding;
 use crate::primitives::grapheme;
 use anyhow::{Context, Result};
 use regex::bytes::Regex;
 use std::io::{self, Read, Seek, SeekFrom, Write};
 use std::ops::Range;
 use std::path::{Path, PathBuf};
-use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
 
 // Large file support configuration
 /// Default threshold for considering a file "large" (100 MB)
@@ -102,6 +105,12 @@ impl LineNumber {
     }
 }
 
+/// Next value for [`TextBuffer::revision`], unique across buffers
+fn next_revision() -> u64 {
+    static NEXT_REVISION: AtomicU64 = AtomicU64::new(1);
+    NEXT_REVISION.fetch_add(1, Ordering::Relaxed)
+}
+
 /// A text buffer that manages document content using a piece table
 /// with integrated line tracking
 pub struct TextBuffer {
@@ -111,6 +120,14 @@ pub struct TextBuffer {
     /// Snapshot of the piece tree root at last save (shared via Arc)
     saved_root: Arc<crate::model::piece_tree::PieceTreeNode>,
 
+    /// Where the piece tree may differ from `saved_root`, so that diffing
+    /// against it can skip the untouched parts
+    saved_dirty: DirtyRegion,
+
+    /// Last result of [`TextBuffer::diff_since_saved`], with the revision it
+    /// was computed at
+    saved_diff_cache: Mutex<Option<(u64, PieceTreeDiff)>>,
+
     /// List of string buffers containing chunks of text data
     /// Index 0 is typically the original/stored buffer
     /// Additional buffers are added for modifications
@@ -130,6 +147,9 @@ pub struct TextBuffer {
     /// clear `modified` (buffer still differs from on-disk file).
     recovery_pending: bool,
 
+    /// Changes on every change to the content (see [`TextBuffer::revision`])
+    revision: u64,
+
     /// Is this a large file (no line indexing, lazy loading enabled)?
     large_file: bool,
 
@@ -145,6 +165,14 @@ pub struct TextBuffer {
     /// changed the line ending format and needs conversion on save.
     original_line_ending: LineEnding,
 
+    /// Encoding of the file on disk; the buffer itself always holds UTF-8
+    encoding: TextEncoding,
+
+    /// Placeholders for bytes of the file that aren't valid UTF-8 (see
+    /// [`crate::model::encoding`]), if it had any; saving writes them back
+    /// as those bytes
+    invalid_bytes: Option<InvalidBytes>,
+
     /// The file size on disk after the last save.
     /// Used for chunked recovery to know the original file size for reconstruction.
     /// Updated when loading from file or after saving.
@@ -165,10 +193,15 @@ impl TextBuffer {
             file_path: None,
             modified: false,
             recovery_pending: false,
+            revision: next_revision(),
+            saved_dirty: DirtyRegion::Clean,
+            saved_diff_cache: Mutex::new(None),
+            invalid_bytes: None,
             large_file: false,
             is_binary: false,
             line_ending,
             original_line_ending: line_ending,
+            encoding: TextEncoding::Utf8,
             saved_file_size: None,
         }
     }
@@ -195,6 +228,7 @@ impl TextBuffer {
         TextBuffer {
             line_ending,
             original_line_ending: line_ending,
+            encoding: TextEncoding::Utf8,
             piece_tree,
             saved_root,
             buffers: vec![buffer],
@@ -202,6 +236,10 @@ impl TextBuffer {
             file_path: None,
             modified: false,
             recovery_pending: false,
+            revision: next_revision(),
+            saved_dirty: DirtyRegion::Clean,
+            saved_diff_cache: Mutex::new(None),
+            invalid_bytes: None,
             large_file: false,
             is_binary: false,
             saved_file_size: Some(bytes), // Treat initial content as "saved" state
@@ -226,10 +264,15 @@ impl TextBuffer {
             file_path: None,
             modified: false,
             recovery_pending: false,
+            revision: next_revision(),
+            saved_dirty: DirtyRegion::Clean,
+            saved_diff_cache: Mutex::new(None),
+            invalid_bytes: None,
             large_file: false,
             is_binary: false,
             line_ending,
             original_line_ending: line_ending,
+            encoding: TextEncoding::Utf8,
             saved_file_size: None,
         }
     }
@@ -266,9 +309,25 @@ impl TextBuffer {
         let mut file = std::fs::File::open(path)?;
         let mut contents = Vec::new();
         file.read_to_end(&mut contents)?;
+        Ok(Self::from_file_contents(path, contents))
+    }
 
+    /// Create a buffer for `path` from contents that were already read
+    ///
+    /// Used when the file was loaded in the background; detects binary content
+    /// and line endings the same way as loading directly. Bytes of a text
+    /// file that aren't valid UTF-8 are replaced by placeholders, which are
+    /// written back unchanged on save.
+    pub fn from_file_contents(path: &Path, contents: Vec<u8>) -> Self {
         // Detect if this is a binary file
         let is_binary = Self::detect_binary(&contents);
+        let escaped = if is_binary {
+            None
+        } else {
+            encoding::escape_invalid_utf8(&contents)
+        };
+        let invalid_bytes = escaped.as_deref().map(InvalidBytes::scan);
+        let contents = escaped.unwrap_or(contents);
 
         // Detect line ending format (CRLF/LF/CR) - used for Enter key insertion
         let line_ending = Self::detect_line_ending(&contents);
@@ -281,7 +340,8 @@ impl TextBuffer {
         buffer.is_binary = is_binary;
         buffer.line_ending = line_ending;
         buffer.original_line_ending = line_ending;
-        Ok(buffer)
+        buffer.invalid_bytes = invalid_bytes;
+        buffer
     }
 
     /// Load a large file with unloaded buffer (no line indexing, lazy loading)
@@ -335,10 +395,15 @@ impl TextBuffer {
             file_path: Some(path.to_path_buf()),
             modified: false,
             recovery_pending: false,
+            revision: next_revision(),
+            saved_dirty: DirtyRegion::Clean,
+            saved_diff_cache: Mutex::new(None),
+            invalid_bytes: None,
             large_file: true,
             is_binary,
             line_ending,
             original_line_ending: line_ending,
+            encoding: TextEncoding::Utf8,
             saved_file_size: Some(file_size),
         })
     }
@@ -362,41 +427,70 @@ impl TextBuffer {
     /// and only keeps edited regions in memory.
     ///
     /// If the line ending format has been changed (via set_line_ending), all content
-    /// will be converted to the new format during save.
+    /// will be converted to the new format during save. Buffers with a non-UTF-8
+    /// encoding (see set_encoding) are written in that encoding.
     pub fn save_to_file<P: AsRef<Path>>(&mut self, path: P) -> io::Result<()> {
         let dest_path = path.as_ref();
-        let total = self.total_bytes();
-
-        // Get original file metadata (permissions, owner, etc.) before writing
-        // so we can preserve it after creating/renaming the temp file
-        let original_metadata = std::fs::metadata(dest_path).ok();
+        let snapshot = self.save_snapshot()?;
+        let new_size = replace_file(dest_path, |out| snapshot.write_to(out))?;
+        self.finish_save(&snapshot, dest_path, new_size);
+        Ok(())
+    }
 
+    /// Capture the contents as they are now, for writing them to disk later
+    ///
+    /// The snapshot applies the conversions of [`Self::save_to_file`]; writing
+    /// it doesn't need the buffer, so it can happen on another thread while
+    /// the buffer keeps being edited.
+    pub fn save_snapshot(&self) -> io::Result<SaveSnapshot> {
         // Check if we need to convert line endings
         let needs_conversion = self.line_ending != self.original_line_ending;
         let target_ending = self.line_ending;
 
-        if total == 0 {
-            // Empty file - just create it
-            std::fs::File::create(dest_path)?;
-            if let Some(ref meta) = original_metadata {
-                Self::restore_file_metadata(dest_path, meta)?;
+        // Re-encoding needs the whole text, so it is converted in one go
+        let encoded = match self.encoding {
+            TextEncoding::Utf8 => match &self.invalid_bytes {
+                Some(invalid) if invalid.bytes() > 0 => {
+                    let text = self.get_all_text().ok_or_else(|| {
+                        io::Error::other(
+                            "Cannot restore invalid bytes of a file that is not fully loaded",
+                        )
+                    })?;
+                    // Restored first, as converting moves the placeholders;
+                    // invalid bytes are never line endings
+                    let text = invalid.restore(&text);
+                    Some(if needs_conversion {
+                        Self::convert_line_endings_to(&text, target_ending)
+                    } else {
+                        text
+                    })
+                }
+                _ => None,
+            },
+            encoding => {
+                let text = self.get_all_text().ok_or_else(|| {
+                    io::Error::other("Cannot re-encode a file that is not fully loaded")
+                })?;
+                let text = if needs_conversion {
+                    Self::convert_line_endings_to(&text, target_ending)
+                } else {
+                    text
+                };
+                Some(encoding.encode(&text))
             }
-            self.file_path = Some(dest_path.to_path_buf());
-            self.mark_saved_snapshot();
-            self.saved_file_size = Some(0);
-            // Update original_line_ending to match the new format
-            self.original_line_ending = self.line_ending;
-            return Ok(());
-        }
-
-        // Use a temp file to avoid corrupting the original if something goes wrong
-        let temp_path = dest_path.with_extension("tmp");
-        let mut out_file = std::fs::File::create(&temp_path)?;
+        };
 
-        // Cache for open source files (for streaming unloaded regions)
-        let mut source_file_cache: Option<(PathBuf, std::fs::File)> = None;
+        let mut snapshot = self.content_snapshot()?;
+        snapshot.convert_to = needs_conversion.then_some(target_ending);
+        snapshot.encoded = encoded;
+        Ok(snapshot)
+    }
 
-        // Iterate through all pieces and write them
+    /// Capture the contents as they are in memory, without the conversions
+    /// applied when saving
+    pub fn content_snapshot(&self) -> io::Result<SaveSnapshot> {
+        let total = self.total_bytes();
+        let mut pieces = Vec::new();
         for piece_view in self.piece_tree.iter_pieces_in_range(0, total) {
             let buffer_id = piece_view.location.buffer_id();
             let buffer = self.buffers.get(buffer_id).ok_or_else(|| {
@@ -405,94 +499,60 @@ impl TextBuffer {
                     format!("Buffer {} not found", buffer_id),
                 )
             })?;
-
-            match &buffer.data {
-                BufferData::Loaded { data, .. } => {
-                    let start = piece_view.buffer_offset;
-                    let end = start + piece_view.bytes;
-                    let chunk = &data[start..end];
-
-                    if needs_conversion {
-                        // Convert line endings before writing
-                        let converted = Self::convert_line_endings_to(chunk, target_ending);
-                        out_file.write_all(&converted)?;
-                    } else {
-                        // Write directly without conversion
-                        out_file.write_all(chunk)?;
-                    }
-                }
+            let start = piece_view.buffer_offset;
+            pieces.push(match &buffer.data {
+                BufferData::Loaded { data, .. } => SnapshotPiece::Loaded {
+                    data: Arc::clone(data),
+                    range: start..start + piece_view.bytes,
+                },
                 BufferData::Unloaded {
                     file_path,
                     file_offset,
                     ..
-                } => {
-                    // Stream from source file
-                    let source_file = match &mut source_file_cache {
-                        Some((cached_path, file)) if cached_path == file_path => file,
-                        _ => {
-                            let file = std::fs::File::open(file_path)?;
-                            source_file_cache = Some((file_path.clone(), file));
-                            &mut source_file_cache.as_mut().unwrap().1
-                        }
-                    };
-
-                    // Seek to the right position in source file
-                    let read_offset = *file_offset + piece_view.buffer_offset;
-                    source_file.seek(SeekFrom::Start(read_offset as u64))?;
-
-                    // Stream in chunks
-                    const STREAM_CHUNK_SIZE: usize = 64 * 1024; // 64KB chunks
-                    let mut remaining = piece_view.bytes;
-                    let mut chunk_buf = vec![0u8; STREAM_CHUNK_SIZE.min(remaining)];
-
-                    while remaining > 0 {
-                        let to_read = remaining.min(chunk_buf.len());
-                        source_file.read_exact(&mut chunk_buf[..to_read])?;
-
-                        if needs_conversion {
-                            // Convert line endings before writing
-                            let converted =
-                                Self::convert_line_endings_to(&chunk_buf[..to_read], target_ending);
-                            out_file.write_all(&converted)?;
-                        } else {
-                            // Write directly without conversion
-                            out_file.write_all(&chunk_buf[..to_read])?;
-                        }
-                        remaining -= to_read;
-                    }
-                }
-            }
-        }
-
-        // Ensure all data is written
-        out_file.sync_all()?;
-        drop(out_file);
-
-        // Restore original file permissions/owner before renaming
-        if let Some(ref meta) = original_metadata {
-            Self::restore_file_metadata(&temp_path, meta)?;
+                } => SnapshotPiece::Unloaded {
+                    file_path: file_path.clone(),
+                    offset: *file_offset + start,
+                    bytes: piece_view.bytes,
+                },
+            });
         }
+        Ok(SaveSnapshot {
+            root: self.piece_tree.root(),
+            pieces,
+            convert_to: None,
+            encoded: None,
+            len: total,
+        })
+    }
 
-        // Atomically replace the original file
-        std::fs::rename(&temp_path, dest_path)?;
-
-        // Update saved file size to match the file on disk
-        let new_size = std::fs::metadata(dest_path)?.len() as usize;
+    /// Record that `snapshot` was written to `path`, giving a file of
+    /// `file_size` bytes
+    ///
+    /// The snapshot becomes the saved baseline, so a buffer edited after the
+    /// snapshot was taken stays modified.
+    pub fn finish_save(&mut self, snapshot: &SaveSnapshot, path: &Path, file_size: usize) {
         tracing::debug!(
             "Buffer::save: updating saved_file_size from {:?} to {}",
             self.saved_file_size,
-            new_size
+            file_size
         );
-        self.saved_file_size = Some(new_size);
-
-        self.file_path = Some(dest_path.to_path_buf());
-        self.mark_saved_snapshot();
-
-        // Update original_line_ending to match what we just saved
-        // This prevents repeated conversions on subsequent saves
-        self.original_line_ending = self.line_ending;
+        self.saved_file_size = Some(file_size);
+        self.file_path = Some(path.to_path_buf());
+        self.saved_root = Arc::clone(&snapshot.root);
+        self.modified = !Arc::ptr_eq(&self.saved_root, &self.piece_tree.root());
+        // Edits made while saving aren't tracked relative to the snapshot
+        self.saved_dirty = if self.modified {
+            DirtyRegion::Unbounded
+        } else {
+            DirtyRegion::Clean
+        };
+        self.invalidate_saved_diff();
 
-        Ok(())
+        // Otherwise original_line_ending is left alone: the contents in
+        // memory still use it, so every later save has to convert them again
+        if snapshot.is_empty() {
+            self.original_line_ending = self.line_ending;
+        }
     }
 
     /// Restore file metadata (permissions, owner/group) from original file
@@ -524,17 +584,42 @@ impl TextBuffer {
         self.piece_tree.total_bytes()
     }
 
+    // Line index
+    //
+    // Every piece tree node counts the line feeds beneath it and every text
+    // buffer keeps its line starts, so line queries are O(log n) and an edit
+    // only updates the pieces it touches. Lines end at '\n', so CRLF files
+    // index the same way, and text after the last line feed is one more
+    // line. Large files have no line metadata: the queries return None there,
+    // and `get_line_number` and `line_col_to_position` estimate instead.
+
     /// Get the total number of lines in the document
-    /// Uses the piece tree's integrated line tracking
     /// Returns None if line count is unknown (e.g., for large files without line indexing)
     pub fn line_count(&self) -> Option<usize> {
         self.piece_tree.line_count()
     }
 
+    /// Get the line (0-indexed) containing a byte offset
+    /// The end of the document is on the last line
+    pub fn line_at_offset(&self, offset: usize) -> Option<usize> {
+        self.offset_to_position(offset).map(|pos| pos.line)
+    }
+
     /// Snapshot the current tree as the saved baseline
     pub fn mark_saved_snapshot(&mut self) {
         self.saved_root = self.piece_tree.root();
         self.modified = false;
+        self.saved_dirty = DirtyRegion::Clean;
+        self.invalidate_saved_diff();
+    }
+
+    /// Drop the cached [`TextBuffer::diff_since_saved`] result, for changes
+    /// that don't bump the revision
+    fn invalidate_saved_diff(&mut self) {
+        *self
+            .saved_diff_cache
+            .get_mut()
+            .unwrap_or_else(|e| e.into_inner()) = None;
     }
 
     /// Diff the current piece tree against the last saved snapshot.
@@ -548,7 +633,27 @@ impl TextBuffer {
     /// - Phase 2: Only compare actual content within changed ranges (O(edit_size))
     ///
     /// This is O(edit_size) instead of O(file_size) for small edits in large files.
+    ///
+    /// Phase 1 only looks at the part of the tree edited since the save (see
+    /// [`DirtyRegion`]), and the result is cached until the next edit, so
+    /// asking repeatedly costs nothing and typing doesn't slow down as the
+    /// file grows.
     pub fn diff_since_saved(&self) -> PieceTreeDiff {
+        let mut cache = self
+            .saved_diff_cache
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        if let Some((revision, diff)) = cache.as_ref() {
+            if *revision == self.revision {
+                return diff.clone();
+            }
+        }
+        let diff = self.compute_diff_since_saved();
+        *cache = Some((self.revision, diff.clone()));
+        diff
+    }
+
+    fn compute_diff_since_saved(&self) -> PieceTreeDiff {
         // First, quick check: if tree roots are identical (Arc pointer equality),
         // the content is definitely the same.
         if Arc::ptr_eq(&self.saved_root, &self.piece_tree.root()) {
@@ -728,6 +833,44 @@ impl TextBuffer {
 
     /// Structure-based diff comparing piece tree leaves
     fn diff_trees_by_structure(&self) -> PieceTreeDiff {
+        if let Some((saved_window, current_window)) = self.saved_dirty.windows() {
+            return self.diff_windows_by_structure(saved_window, current_window);
+        }
+        self.diff_whole_trees_by_structure()
+    }
+
+    /// Structure-based diff limited to the windows that may have changed
+    fn diff_windows_by_structure(
+        &self,
+        saved_window: Range<usize>,
+        current_window: Range<usize>,
+    ) -> PieceTreeDiff {
+        let mut diff = crate::model::piece_tree_diff::diff_piece_tree_windows(
+            &self.saved_root,
+            saved_window,
+            &self.piece_tree.root(),
+            current_window,
+        );
+        if diff.equal {
+            return diff;
+        }
+
+        // Line ranges come from the line index rather than from counting
+        // the line feeds before or inside each range
+        diff.line_ranges = diff
+            .byte_ranges
+            .iter()
+            .map(|range| {
+                let start_line = self.line_at_offset(range.start)?;
+                let end_line = self.line_at_offset(range.end)?;
+                Some(start_line..end_line + 1)
+            })
+            .collect();
+        diff
+    }
+
+    /// Structure-based diff comparing all piece tree leaves
+    fn diff_whole_trees_by_structure(&self) -> PieceTreeDiff {
         crate::model::piece_tree_diff::diff_piece_trees(
             &self.saved_root,
             &self.piece_tree.root(),
@@ -735,13 +878,10 @@ impl TextBuffer {
                 if len == 0 {
                     return Some(0);
                 }
-                let buf = self.buffers.get(leaf.location.buffer_id())?;
-                let data = buf.get_data()?;
                 let start = leaf.offset + start;
-                let end = start + len;
-                let slice = data.get(start..end)?;
-                let line_feeds = slice.iter().filter(|&&b| b == b'\n').count();
-                Some(line_feeds)
+                self.buffers
+                    .get(leaf.location.buffer_id())?
+                    .line_feeds_in(start, start + len)
             },
         )
     }
@@ -767,7 +907,14 @@ impl TextBuffer {
 
         // Mark as modified and needing recovery
         self.modified = true;
+        self.revision = next_revision();
         self.recovery_pending = true;
+        self.saved_dirty
+            .insert(offset.min(self.total_bytes()), text.len());
+        let total_bytes = self.total_bytes();
+        if let Some(invalid) = &mut self.invalid_bytes {
+            invalid.insert(offset.min(total_bytes), &text);
+        }
 
         // Count line feeds in the text to insert
         let line_feed_cnt = Some(text.iter().filter(|&&b| b == b'\n').count());
@@ -810,6 +957,7 @@ impl TextBuffer {
 
         // Mark as modified and needing recovery
         self.modified = true;
+        self.revision = next_revision();
         self.recovery_pending = true;
 
         // Find the piece containing the byte just before the insertion point
@@ -861,7 +1009,15 @@ impl TextBuffer {
 
         // Mark as modified and needing recovery
         self.modified = true;
+        self.revision = next_revision();
         self.recovery_pending = true;
+        self.saved_dirty = DirtyRegion::Unbounded;
+        if self.invalid_bytes.is_some() {
+            let offset = self.position_to_offset(position);
+            if let Some(invalid) = &mut self.invalid_bytes {
+                invalid.insert(offset, &text);
+            }
+        }
 
         // Count line feeds in the text to insert
         let line_feed_cnt = text.iter().filter(|&&b| b == b'\n').count();
@@ -890,12 +1046,19 @@ impl TextBuffer {
             return;
         }
 
+        let end = (offset + bytes).min(self.total_bytes());
+        if let Some(invalid) = &mut self.invalid_bytes {
+            invalid.delete(offset..end);
+        }
+
         // Update piece tree
         self.piece_tree.delete(offset, bytes, &self.buffers);
 
         // Mark as modified and needing recovery
         self.modified = true;
+        self.revision = next_revision();
         self.recovery_pending = true;
+        self.saved_dirty.delete(offset..end);
     }
 
     /// Delete text in a range
@@ -908,6 +1071,12 @@ impl TextBuffer {
     /// Delete text in a line/column range
     /// This now uses the optimized piece_tree.delete_position_range() for a single traversal
     pub fn delete_range(&mut self, start: Position, end: Position) {
+        if self.invalid_bytes.is_some() {
+            let range = self.position_to_offset(start)..self.position_to_offset(end);
+            if let Some(invalid) = &mut self.invalid_bytes {
+                invalid.delete(range);
+            }
+        }
         // Use the optimized position-based deletion
         self.piece_tree.delete_position_range(
             start.line,
@@ -918,7 +1087,9 @@ impl TextBuffer {
         );
         // Mark as modified and needing recovery
         self.modified = true;
+        self.revision = next_revision();
         self.recovery_pending = true;
+        self.saved_dirty = DirtyRegion::Unbounded;
     }
 
     /// Replace the entire buffer content with new content
@@ -930,6 +1101,9 @@ impl TextBuffer {
     pub fn replace_content(&mut self, new_content: &str) {
         let bytes = new_content.len();
         let content_bytes = new_content.as_bytes().to_vec();
+        if let Some(invalid) = &mut self.invalid_bytes {
+            invalid.reset(&content_bytes);
+        }
 
         // Count line feeds in the new content
         let line_feed_cnt = content_bytes.iter().filter(|&&b| b == b'\n').count();
@@ -954,15 +1128,26 @@ impl TextBuffer {
 
         // Mark as modified and needing recovery
         self.modified = true;
+        self.revision = next_revision();
         self.recovery_pending = true;
+        self.saved_dirty = DirtyRegion::Unbounded;
     }
 
     /// Restore a previously saved piece tree (for undo of BulkEdit)
     /// This is O(1) because PieceTree uses Arc internally
     pub fn restore_piece_tree(&mut self, tree: &Arc<PieceTree>) {
         self.piece_tree = (**tree).clone();
+        if self.invalid_bytes.is_some() {
+            if let Some(text) = self.get_all_text() {
+                if let Some(invalid) = &mut self.invalid_bytes {
+                    invalid.reset(&text);
+                }
+            }
+        }
         self.modified = true;
+        self.revision = next_revision();
         self.recovery_pending = true;
+        self.saved_dirty = DirtyRegion::Unbounded;
     }
 
     /// Get the current piece tree as an Arc (for saving before BulkEdit)
@@ -971,28 +1156,100 @@ impl TextBuffer {
         Arc::new(self.piece_tree.clone())
     }
 
+    /// Bytes the text storage takes, including text only the saved snapshot
+    /// or trees kept for undo still refer to
+    pub fn memory_bytes(&self) -> usize {
+        self.buffers.iter().map(StringBuffer::memory_bytes).sum()
+    }
+
+    /// Copy the text into one newly allocated buffer and release the text
+    /// no tree refers to any more, returning the bytes released
+    ///
+    /// `retained` are trees that have to stay readable, such as those the
+    /// undo history holds. The content doesn't change. Buffers with unsaved
+    /// changes are left alone, since the saved snapshot would then share no
+    /// pieces with the text and every line would diff as changed, and so
+    /// are large files whose text isn't all loaded.
+    pub fn compact(&mut self, retained: &[Arc<PieceTree>]) -> usize {
+        if self.large_file
+            || !self.buffers.iter().all(StringBuffer::is_loaded)
+            || !Arc::ptr_eq(&self.saved_root, &self.piece_tree.root())
+        {
+            return 0;
+        }
+        let Some(mut content) = self.get_all_text() else {
+            return 0;
+        };
+        content.shrink_to_fit();
+        let before = self.memory_bytes();
+
+        let bytes = content.len();
+        let buffer_id = self.next_buffer_id;
+        self.next_buffer_id += 1;
+        let buffer = StringBuffer::new(buffer_id, content);
+        let line_feed_cnt = buffer.line_feed_count();
+        self.buffers.push(buffer);
+        self.piece_tree = if bytes > 0 {
+            // Stored like a file's text: it is what was saved
+            PieceTree::new(BufferLocation::Stored(buffer_id), 0, bytes, line_feed_cnt)
+        } else {
+            PieceTree::empty()
+        };
+        self.saved_root = self.piece_tree.root();
+        self.invalidate_saved_diff();
+
+        let mut live = std::collections::HashSet::new();
+        self.piece_tree.collect_buffer_ids(&mut live);
+        for tree in retained {
+            tree.collect_buffer_ids(&mut live);
+        }
+        for buffer in &mut self.buffers {
+            if !live.contains(&buffer.id) {
+                buffer.release();
+            }
+        }
+        before.saturating_sub(self.memory_bytes())
+    }
+
     /// Apply bulk edits efficiently in a single pass
     /// Returns the net change in bytes
     pub fn apply_bulk_edits(&mut self, edits: &[(usize, usize, &str)]) -> isize {
-        // Pre-allocate buffers for all insert texts (only non-empty texts)
-        // This avoids the borrow conflict in the closure
+        // All the inserted texts go into one new buffer, so that typing with
+        // thousands of cursors adds one buffer rather than thousands. This
+        // also avoids the borrow conflict in the closure
         // IMPORTANT: Only add entries for non-empty texts because the closure
         // is only called for edits with non-empty insert text
         let mut buffer_info: Vec<(BufferLocation, usize, usize, Option<usize>)> = Vec::new();
+        let mut content: Vec<u8> = Vec::new();
+        let buffer_id = self.next_buffer_id;
+
+        // The edits come last first, so each leaves the offsets of the
+        // rest alone
+        if let Some(invalid) = &mut self.invalid_bytes {
+            let total_bytes = self.piece_tree.total_bytes();
+            for &(pos, del_len, text) in edits {
+                invalid.delete(pos..pos.saturating_add(del_len).min(total_bytes));
+                invalid.insert(pos, text.as_bytes());
+            }
+        }
 
         for (_, _, text) in edits {
             if !text.is_empty() {
-                let buffer_id = self.next_buffer_id;
-                self.next_buffer_id += 1;
-                let content = text.as_bytes().to_vec();
-                let lf_cnt = content.iter().filter(|&&b| b == b'\n').count();
-                let bytes = content.len();
-                let buffer = StringBuffer::new(buffer_id, content);
-                self.buffers.push(buffer);
-                buffer_info.push((BufferLocation::Added(buffer_id), 0, bytes, Some(lf_cnt)));
+                let lf_cnt = text.bytes().filter(|&b| b == b'\n').count();
+                buffer_info.push((
+                    BufferLocation::Added(buffer_id),
+                    content.len(),
+                    text.len(),
+                    Some(lf_cnt),
+                ));
+                content.extend_from_slice(text.as_bytes());
             }
             // No placeholder for empty texts - the closure is only called for non-empty texts
         }
+        if !content.is_empty() {
+            self.next_buffer_id += 1;
+            self.buffers.push(StringBuffer::new(buffer_id, content));
+        }
 
         // Now call apply_bulk_edits with a simple index-based closure
         let mut idx = 0;
@@ -1005,7 +1262,9 @@ impl TextBuffer {
             });
 
         self.modified = true;
+        self.revision = next_revision();
         self.recovery_pending = true;
+        self.saved_dirty = DirtyRegion::Unbounded;
         delta
     }
 
@@ -1151,6 +1410,11 @@ impl TextBuffer {
                             chunk_bytes,
                             BufferLocation::Added(new_buffer_id),
                         );
+                        // Same bytes, but no longer the saved tree's pieces
+                        self.saved_dirty
+                            .delete(split_start_in_doc..split_end_in_doc);
+                        self.saved_dirty.insert(split_start_in_doc, chunk_bytes);
+                        self.invalidate_saved_diff();
 
                         // Load the chunk buffer
                         self.buffers
@@ -1350,6 +1614,16 @@ impl TextBuffer {
             None, // line_feed_cnt unknown for unloaded chunk
             &self.buffers,
         );
+        self.saved_dirty.insert(old_size, additional_bytes);
+        self.invalidate_saved_diff();
+    }
+
+    /// Revision of the content
+    ///
+    /// It changes whenever the content does, and no two buffers share one, so
+    /// a value cached with a revision stays valid while the revision matches.
+    pub fn revision(&self) -> u64 {
+        self.revision
     }
 
     /// Check if the buffer has been modified since last save
@@ -1462,6 +1736,34 @@ impl TextBuffer {
         self.recovery_pending = true;
     }
 
+    /// Number of invalid byte sequences of the file kept as placeholders
+    pub fn invalid_byte_sequences(&self) -> usize {
+        self.invalid_bytes
+            .as_ref()
+            .map_or(0, InvalidBytes::sequences)
+    }
+
+    /// Number of invalid bytes whose placeholders were removed since the
+    /// last call; those bytes won't be written back on save
+    pub fn take_dropped_invalid_bytes(&mut self) -> usize {
+        self.invalid_bytes
+            .as_mut()
+            .map_or(0, InvalidBytes::take_dropped)
+    }
+
+    /// Get the encoding the file is saved in
+    pub fn encoding(&self) -> TextEncoding {
+        self.encoding
+    }
+
+    /// Set the encoding the file is saved in
+    ///
+    /// The buffer contents stay UTF-8 and are not marked as modified; only
+    /// the bytes written by the next save change.
+    pub fn set_encoding(&mut self, encoding: TextEncoding) {
+        self.encoding = encoding;
+    }
+
     /// Set the default line ending format for a new/empty buffer
     ///
     /// Unlike `set_line_ending`, this does NOT mark the buffer as modified.
@@ -1834,8 +2136,24 @@ impl TextBuffer {
         }
     }
 
+    /// Find the first match of a regex pattern within a range, without
+    /// wrap-around, returning the bytes it spans
+    pub fn find_regex_match_in_range(
+        &self,
+        regex: &Regex,
+        range: Range<usize>,
+    ) -> Option<Range<usize>> {
+        self.find_regex_match(range.start, range.end.min(self.len()), regex)
+    }
+
     /// Find regex pattern in a byte range using overlapping chunks
     fn find_regex(&self, start: usize, end: usize, regex: &Regex) -> Option<usize> {
+        self.find_regex_match(start, end, regex)
+            .map(|found| found.start)
+    }
+
+    /// Find the bytes spanned by a regex match in a byte range
+    fn find_regex_match(&self, start: usize, end: usize, regex: &Regex) -> Option<Range<usize>> {
         if start >= end {
             return None;
         }
@@ -1858,7 +2176,7 @@ impl TextBuffer {
                     // Verify the match doesn't extend beyond our search range
                     let match_len = mat.end() - mat.start();
                     if absolute_pos + match_len <= end {
-                        return Some(absolute_pos);
+                        return Some(absolute_pos..absolute_pos + match_len);
                     }
                 }
             }
@@ -1993,30 +2311,56 @@ impl TextBuffer {
         }
     }
 
-    /// Convert byte position to LSP position (line, UTF-16 code units)
-    /// LSP protocol uses UTF-16 code units for character offsets
-    pub fn position_to_lsp_position(&self, byte_pos: usize) -> (usize, usize) {
+    /// Convert byte position to LSP position (line, character offset in
+    /// `encoding`'s units)
+    pub fn position_to_lsp_position(
+        &self,
+        byte_pos: usize,
+        encoding: PositionEncoding,
+    ) -> (usize, usize) {
         let (line, column_bytes) = self
             .offset_to_position(byte_pos)
             .map(|pos| (pos.line, pos.column))
             .unwrap_or_else(|| (byte_pos / 80, 0)); // Estimate if metadata unavailable
 
+        // UTF-8 offsets are the byte column, no need to read the line
+        if encoding == PositionEncoding::Utf8 {
+            return (line, column_bytes);
+        }
+
         // Get the line content
         if let Some(line_bytes) = self.get_line(line) {
-            // Convert byte offset to UTF-16 code units
+            // Convert byte offset to the encoding's units
             let text_before = &line_bytes[..column_bytes.min(line_bytes.len())];
             let text_str = String::from_utf8_lossy(text_before);
-            let utf16_offset = text_str.encode_utf16().count();
-            (line, utf16_offset)
+            (line, encoding.units(&text_str))
         } else {
             (line, 0)
         }
     }
 
-    /// Convert LSP position (line, UTF-16 code units) to byte position
-    /// LSP uses UTF-16 code units for character offsets, not bytes
+    /// Convert LSP position (line, character offset in `encoding`'s units)
+    /// to byte position
+    ///
+    /// An offset inside a character counts as after it.
     /// Optimized to use single line_range() call instead of two
-    pub fn lsp_position_to_byte(&self, line: usize, utf16_offset: usize) -> usize {
+    pub fn lsp_position_to_byte(
+        &self,
+        line: usize,
+        character: usize,
+        encoding: PositionEncoding,
+    ) -> usize {
+        if encoding == PositionEncoding::Utf8 {
+            // The offset is the byte column, only kept off the middle of a
+            // character
+            let pos = self.line_col_to_position(line, character);
+            return if self.snap_to_char_boundary(pos) == pos {
+                pos
+            } else {
+                self.next_char_boundary(pos)
+            };
+        }
+
         if let Some((line_start, end)) = self.piece_tree.line_range(line, &self.buffers) {
             // Calculate line length and get line content
             let line_len = if let Some(end_offset) = end {
@@ -2031,20 +2375,7 @@ impl TextBuffer {
                     return line_start;
                 };
                 let line_str = String::from_utf8_lossy(&line_bytes);
-
-                // Convert UTF-16 offset to byte offset
-                let mut utf16_count = 0;
-                let mut byte_offset = 0;
-
-                for ch in line_str.chars() {
-                    if utf16_count >= utf16_offset {
-                        break;
-                    }
-                    utf16_count += ch.len_utf16();
-                    byte_offset += ch.len_utf8();
-                }
-
-                line_start + byte_offset
+                line_start + encoding.byte_offset(&line_str, character)
             } else {
                 line_start
             }
@@ -2352,76 +2683,32 @@ impl TextBuffer {
     /// Large files don't maintain line metadata for performance reasons. The estimation
     /// assumes ~80 bytes per line on average, which works reasonably well for most text files.
     pub fn get_line_number(&self, byte_offset: usize) -> usize {
-        self.offset_to_position(byte_offset)
-            .map(|pos| pos.line)
-            .unwrap_or_else(|| {
-                // Estimate line number based on average line length of ~80 bytes
-                byte_offset / 80
-            })
+        self.line_at_offset(byte_offset).unwrap_or_else(|| {
+            // Estimate line number based on average line length of ~80 bytes
+            byte_offset / 80
+        })
     }
 
-    /// Get the starting line number at a byte offset (used for viewport rendering)
-    ///
-    /// # Line Cache Architecture (Post-Refactoring):
-    ///
-    /// The concept of a separate "line cache" is **now obsolete**. After the refactoring,
-    /// line tracking is integrated directly into the piece tree via:
-    /// ```rust
-    /// BufferData::Loaded {
-    ///     data: Vec<u8>,
-    ///     line_starts: Option<Vec<usize>>  // None = large file mode (no line metadata)
-    /// }
-    /// ```
-    ///
-    /// ## Why This Method Still Exists:
-    /// The rendering code needs to know what line number to display in the margin at the
-    /// top of the viewport. This method returns that line number, handling both small
-    /// and large file modes transparently.
-    ///
-    /// ## Small vs Large File Modes:
-    /// - **Small files**: `line_starts = Some(vec)` → returns exact line number from metadata
-    /// - **Large files**: `line_starts = None` → returns estimated line number (byte_offset / 80)
-    ///
-    /// ## Legacy Line Cache Methods:
-    /// These methods are now no-ops and can be removed in a future cleanup:
-    /// - `invalidate_line_cache_from()` - No-op (piece tree updates automatically)
-    /// - `handle_line_cache_insertion()` - No-op (piece tree updates automatically)
-    /// - `handle_line_cache_deletion()` - No-op (piece tree updates automatically)
-    /// - `clear_line_cache()` - No-op (can't clear piece tree metadata)
+    /// Number of characters (Unicode scalar values) in `range`, counted
+    /// piece by piece without copying the text
     ///
-    /// ## Bug Fix (2025-11):
-    /// Previously this method always returned `0`, causing line numbers in the margin
-    /// to always show 1, 2, 3... regardless of scroll position. Now it correctly returns
-    /// the actual line number at `start_byte`.
-    pub fn populate_line_cache(&mut self, start_byte: usize, _line_count: usize) -> usize {
-        // No-op for cache population: LineIndex maintains all line starts automatically
-        // But we need to return the actual line number at start_byte for rendering
-        self.get_line_number(start_byte)
-    }
-
-    /// Get cached byte offset for line (compatibility method)
-    pub fn get_cached_byte_offset_for_line(&self, line_number: usize) -> Option<usize> {
-        self.line_start_offset(line_number)
-    }
-
-    /// Invalidate line cache from offset (no-op in new implementation)
-    pub fn invalidate_line_cache_from(&mut self, _byte_offset: usize) {
-        // No-op: LineIndex updates automatically
-    }
-
-    /// Handle line cache insertion (no-op in new implementation)
-    pub fn handle_line_cache_insertion(&mut self, _byte_offset: usize, _bytes_inserted: usize) {
-        // No-op: LineIndex updates automatically during insert
-    }
-
-    /// Handle line cache deletion (no-op in new implementation)
-    pub fn handle_line_cache_deletion(&mut self, _byte_offset: usize, _bytes_deleted: usize) {
-        // No-op: LineIndex updates automatically during delete
-    }
-
-    /// Clear line cache (no-op in new implementation)
-    pub fn clear_line_cache(&mut self) {
-        // No-op: LineIndex can't be cleared
+    /// Returns None if part of the range isn't loaded, as in large files.
+    pub fn char_count_in_range(&self, range: Range<usize>) -> Option<usize> {
+        let end = range.end.min(self.len());
+        let mut count = 0;
+        for piece in self.piece_tree.iter_pieces_in_range(range.start, end) {
+            let start = range.start.max(piece.doc_offset);
+            let stop = end.min(piece.doc_offset + piece.bytes);
+            if stop <= start {
+                continue;
+            }
+            let data = self.buffers.get(piece.location.buffer_id())?.get_data()?;
+            let from = piece.buffer_offset + (start - piece.doc_offset);
+            let bytes = data.get(from..from + (stop - start))?;
+            // Every byte but UTF-8 continuation bytes starts a character
+            count += bytes.iter().filter(|&&b| b & 0xC0 != 0x80).count();
+        }
+        Some(count)
     }
 
     // Test helper methods
@@ -2439,6 +2726,131 @@ impl TextBuffer {
     }
 }
 
+/// The contents of a buffer at one moment, for writing them to disk off the
+/// main thread
+///
+/// Taking one copies only the list of pieces: the piece tree is persistent,
+/// loaded text is shared with the buffer, and unloaded regions are streamed
+/// from their file when written.
+pub struct SaveSnapshot {
+    /// Root of the piece tree the pieces were taken from
+    root: Arc<crate::model::piece_tree::PieceTreeNode>,
+    pieces: Vec<SnapshotPiece>,
+    /// Line ending the contents are converted to while writing, if any
+    convert_to: Option<LineEnding>,
+    /// The whole contents already encoded, for non-UTF-8 files
+    encoded: Option<Vec<u8>>,
+    len: usize,
+}
+
+enum SnapshotPiece {
+    Loaded {
+        data: Arc<Vec<u8>>,
+        range: Range<usize>,
+    },
+    Unloaded {
+        file_path: PathBuf,
+        offset: usize,
+        bytes: usize,
+    },
+}
+
+impl SaveSnapshot {
+    /// Size of the contents in memory, before any conversion
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Write the contents, streaming unloaded regions from their file
+    pub fn write_to(&self, out: &mut dyn Write) -> io::Result<()> {
+        if let Some(bytes) = &self.encoded {
+            return out.write_all(bytes);
+        }
+
+        let mut write_chunk = |chunk: &[u8]| match self.convert_to {
+            // Convert line endings before writing
+            Some(ending) => out.write_all(&TextBuffer::convert_line_endings_to(chunk, ending)),
+            None => out.write_all(chunk),
+        };
+
+        // Cache for open source files (for streaming unloaded regions)
+        let mut source_file_cache: Option<(&Path, std::fs::File)> = None;
+
+        for piece in &self.pieces {
+            match piece {
+                SnapshotPiece::Loaded { data, range } => write_chunk(&data[range.clone()])?,
+                SnapshotPiece::Unloaded {
+                    file_path,
+                    offset,
+                    bytes,
+                } => {
+                    // Stream from source file
+                    let source_file = match &mut source_file_cache {
+                        Some((cached_path, file)) if *cached_path == file_path.as_path() => file,
+                        _ => {
+                            let file = std::fs::File::open(file_path)?;
+                            &mut source_file_cache.insert((file_path, file)).1
+                        }
+                    };
+                    source_file.seek(SeekFrom::Start(*offset as u64))?;
+
+                    // Stream in chunks
+                    const STREAM_CHUNK_SIZE: usize = 64 * 1024; // 64KB chunks
+                    let mut remaining = *bytes;
+                    let mut chunk_buf = vec![0u8; STREAM_CHUNK_SIZE.min(remaining)];
+                    while remaining > 0 {
+                        let to_read = remaining.min(chunk_buf.len());
+                        source_file.read_exact(&mut chunk_buf[..to_read])?;
+                        write_chunk(&chunk_buf[..to_read])?;
+                        remaining -= to_read;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Replace the file at `dest_path` with what `write` produces
+///
+/// The contents go to a temporary file next to it that is renamed over the
+/// original once complete, so a failed write leaves the original intact.
+/// The original's permissions and owner are kept. Returns the size of the
+/// new file.
+pub fn replace_file(
+    dest_path: &Path,
+    write: impl FnOnce(&mut dyn Write) -> io::Result<()>,
+) -> io::Result<usize> {
+    // Get original file metadata (permissions, owner, etc.) before writing
+    // so we can preserve it after creating/renaming the temp file
+    let original_metadata = std::fs::metadata(dest_path).ok();
+
+    // Use a temp file to avoid corrupting the original if something goes wrong
+    let temp_path = dest_path.with_extension("tmp");
+    let mut out_file = std::fs::File::create(&temp_path)?;
+    // Ensure all data is written
+    let written = write(&mut out_file).and_then(|()| out_file.sync_all());
+    drop(out_file);
+    if let Err(e) = written {
+        let _ = std::fs::remove_file(&temp_path);
+        return Err(e);
+    }
+
+    // Restore original file permissions/owner before renaming
+    if let Some(ref meta) = original_metadata {
+        TextBuffer::restore_file_metadata(&temp_path, meta)?;
+    }
+
+    // Atomically replace the original file
+    std::fs::rename(&temp_path, dest_path)?;
+
+    Ok(std::fs::metadata(dest_path)?.len() as usize)
+}
+
 /// Type alias for backwards compatibility
 pub type Buffer = TextBuffer;
 
@@ -2868,6 +3280,18 @@ mod tests {
         assert_eq!(buffer.get_text_range(0, 11), Some(b"hello world".to_vec()));
     }
 
+    #[test]
+    fn test_char_count_in_range_spans_pieces() {
+        let mut buffer = TextBuffer::from_bytes("añb".as_bytes().to_vec());
+        buffer.insert_bytes(1, "日本".as_bytes().to_vec());
+
+        // "a日本ñb": 1 + 3 + 3 + 2 + 1 bytes
+        assert_eq!(buffer.char_count_in_range(0..buffer.len()), Some(5));
+        assert_eq!(buffer.char_count_in_range(1..7), Some(2));
+        assert_eq!(buffer.char_count_in_range(4..9), Some(2));
+        assert_eq!(buffer.char_count_in_range(3..3), Some(0));
+    }
+
     #[test]
     fn test_empty_operations() {
         let mut buffer = TextBuffer::from_bytes(b"hello".to_vec());
@@ -2897,6 +3321,75 @@ mod tests {
         assert_eq!(buffer.get_all_text().unwrap(), b"ba");
     }
 
+    #[test]
+    fn test_compact_keeps_content_and_releases_text() {
+        let mut buffer = TextBuffer::from_bytes(b"hello\nworld\n".to_vec());
+        let undo_tree = buffer.snapshot_piece_tree();
+        // Inserting at the start allocates a buffer per insertion
+        for i in 0..200 {
+            buffer.insert_bytes(0, vec![b'a' + (i % 26) as u8]);
+        }
+        let content = buffer.get_all_text().unwrap();
+
+        // Unsaved changes keep the pieces the saved diff relies on
+        assert_eq!(buffer.compact(&[]), 0);
+
+        buffer.mark_saved_snapshot();
+        let before = buffer.memory_bytes();
+        let freed = buffer.compact(&[Arc::clone(&undo_tree)]);
+        assert!(freed > 0);
+        assert_eq!(buffer.memory_bytes(), before - freed);
+        assert_eq!(buffer.get_all_text().unwrap(), content);
+        assert!(buffer.diff_since_saved().equal);
+
+        // Edits still work, and the retained tree is still readable
+        buffer.insert_bytes(content.len() - 1, b"!".to_vec());
+        assert!(buffer.get_all_text().unwrap().ends_with(b"world!\n"));
+        buffer.restore_piece_tree(&undo_tree);
+        assert_eq!(buffer.get_all_text().unwrap(), b"hello\nworld\n");
+    }
+
+    /// Typing in the middle of a file and diffing against the saved snapshot
+    /// after every keystroke, as the plugin state snapshot does
+    fn time_typing_with_saved_diffs(lines: usize) -> std::time::Duration {
+        let content = "some line of text in a fairly large file\n".repeat(lines);
+        let mut buffer = TextBuffer::from_str(&content, 0);
+        let mut offset = content.len() / 2;
+
+        let start = std::time::Instant::now();
+        for _ in 0..500 {
+            buffer.insert_bytes(offset, b"x".to_vec());
+            offset += 1;
+            let diff = buffer.diff_since_saved();
+            assert!(!diff.equal);
+        }
+        start.elapsed()
+    }
+
+    /// Run with: cargo test --lib test_saved_diff_typing_performance -- --ignored --nocapture
+    #[test]
+    #[ignore]
+    fn test_saved_diff_typing_performance() {
+        let small = time_typing_with_saved_diffs(10_000);
+        let large = time_typing_with_saved_diffs(200_000);
+
+        println!("\n=== Saved Diff Typing Performance ===");
+        println!("10k lines:  {:?} for 500 keystrokes", small);
+        println!("200k lines: {:?} for 500 keystrokes", large);
+        println!(
+            "Ratio: {:.1}x for a 20x larger file",
+            large.as_secs_f64() / small.as_secs_f64()
+        );
+
+        // Diffing the whole file would make this ratio about 20x
+        assert!(
+            large < small * 4,
+            "Typing cost should not scale with file size: {:?} vs {:?}",
+            large,
+            small
+        );
+    }
+
     // ===== Phase 1-3: Large File Support Tests =====
 
     mod large_file_support {
@@ -3691,7 +4184,7 @@ mod tests {
 
         // Verify initial positions work correctly
         // Position 23 is 'v' of second "val" on line 1
-        let (line, char) = buffer.position_to_lsp_position(23);
+        let (line, char) = buffer.position_to_lsp_position(23, PositionEncoding::Utf16);
         assert_eq!(line, 1, "Initial: position 23 should be on line 1");
         assert_eq!(char, 4, "Initial: position 23 should be at char 4");
 
@@ -3724,7 +4217,7 @@ mod tests {
         // Position 25 = 21 + 4 = line 1, char 4
 
         // The workaround in position_to_lsp_position should give correct result
-        let (line, char) = buffer.position_to_lsp_position(25);
+        let (line, char) = buffer.position_to_lsp_position(25, PositionEncoding::Utf16);
         assert_eq!(
             line, 1,
             "After modification: position 25 should be on line 1"
@@ -3735,11 +4228,51 @@ mod tests {
         );
 
         // Also verify position 21 (start of line 1) works
-        let (line, char) = buffer.position_to_lsp_position(21);
+        let (line, char) = buffer.position_to_lsp_position(21, PositionEncoding::Utf16);
         assert_eq!(line, 1, "Position 21 should be on line 1");
         assert_eq!(char, 0, "Position 21 should be at char 0 (start of line)");
     }
 
+    #[test]
+    fn test_lsp_positions_on_multi_byte_lines() {
+        // "é" is 2 bytes, "😀" 4 bytes and a UTF-16 surrogate pair
+        let buffer = TextBuffer::from_bytes("ab\né😀x\n😀😀\n".as_bytes().to_vec());
+        let x = 3 + "é😀".len();
+        let second_emoji = x + 2 + "😀".len();
+        let cases = [
+            (PositionEncoding::Utf8, (1, 6), (2, 4)),
+            (PositionEncoding::Utf16, (1, 3), (2, 2)),
+            (PositionEncoding::Utf32, (1, 2), (2, 1)),
+        ];
+        for (encoding, x_position, emoji_position) in cases {
+            assert_eq!(buffer.position_to_lsp_position(x, encoding), x_position);
+            assert_eq!(
+                buffer.position_to_lsp_position(second_emoji, encoding),
+                emoji_position
+            );
+            assert_eq!(
+                buffer.lsp_position_to_byte(x_position.0, x_position.1, encoding),
+                x
+            );
+            assert_eq!(
+                buffer.lsp_position_to_byte(emoji_position.0, emoji_position.1, encoding),
+                second_emoji
+            );
+            assert_eq!(buffer.lsp_position_to_byte(0, 1, encoding), 1);
+            assert_eq!(buffer.lsp_position_to_byte(9, 0, encoding), buffer.len());
+        }
+
+        // Offsets inside a character count as after it
+        assert_eq!(
+            buffer.lsp_position_to_byte(2, 1, PositionEncoding::Utf16),
+            second_emoji
+        );
+        assert_eq!(
+            buffer.lsp_position_to_byte(1, 3, PositionEncoding::Utf8),
+            x
+        );
+    }
+
     #[test]
     fn test_detect_crlf() {
         assert_eq!(
@@ -3953,6 +4486,81 @@ mod tests {
             assert_eq!(&saved_bytes, b"Line 1\nLine 2\nLine 3\n");
         }
     }
+
+    mod invalid_utf8 {
+        use super::*;
+        use tempfile::TempDir;
+
+        #[test]
+        fn test_invalid_bytes_restored_on_save() {
+            let temp_dir = TempDir::new().unwrap();
+            let file_path = temp_dir.path().join("mixed.txt");
+            let original = b"caf\xe9\r\nx \xff\xfe y\r\n";
+            std::fs::write(&file_path, original).unwrap();
+
+            let mut buffer =
+                TextBuffer::load_from_file(&file_path, DEFAULT_LARGE_FILE_THRESHOLD).unwrap();
+            assert!(!buffer.is_binary());
+            assert_eq!(buffer.invalid_byte_sequences(), 2);
+            let text = buffer.to_string().unwrap();
+            assert!(!text.contains('\u{FFFD}'));
+
+            // Unedited, the file is written back byte for byte
+            buffer.save_to_file(&file_path).unwrap();
+            assert_eq!(std::fs::read(&file_path).unwrap(), original);
+
+            // Line ending conversion keeps the bytes too
+            buffer.insert(0, "> ");
+            buffer.set_line_ending(LineEnding::LF);
+            buffer.save_to_file(&file_path).unwrap();
+            assert_eq!(
+                std::fs::read(&file_path).unwrap(),
+                b"> caf\xe9\nx \xff\xfe y\n"
+            );
+        }
+
+        #[test]
+        fn test_deleted_placeholders_counted_as_dropped() {
+            let temp_dir = TempDir::new().unwrap();
+            let file_path = temp_dir.path().join("mixed.txt");
+            std::fs::write(&file_path, b"a\xff\xfeb\xc0").unwrap();
+            let mut buffer =
+                TextBuffer::load_from_file(&file_path, DEFAULT_LARGE_FILE_THRESHOLD).unwrap();
+            assert_eq!(buffer.invalid_byte_sequences(), 2);
+            assert_eq!(buffer.take_dropped_invalid_bytes(), 0);
+
+            // "a" + two 4-byte placeholders: delete the first placeholder
+            let text = buffer.to_string().unwrap();
+            let deleted: String = text.chars().skip(1).take(1).collect();
+            buffer.delete(1..5);
+            assert_eq!(buffer.take_dropped_invalid_bytes(), 1);
+            assert_eq!(buffer.take_dropped_invalid_bytes(), 0);
+
+            // Putting it back (as undo does) drops nothing
+            buffer.insert(1, &deleted);
+            assert_eq!(buffer.take_dropped_invalid_bytes(), 0);
+            buffer.save_to_file(&file_path).unwrap();
+            assert_eq!(std::fs::read(&file_path).unwrap(), b"a\xff\xfeb\xc0");
+        }
+
+        #[test]
+        fn test_placeholder_characters_of_valid_file_saved_as_text() {
+            let temp_dir = TempDir::new().unwrap();
+            let file_path = temp_dir.path().join("valid.txt");
+            std::fs::write(&file_path, "ok\n").unwrap();
+            let mut buffer =
+                TextBuffer::load_from_file(&file_path, DEFAULT_LARGE_FILE_THRESHOLD).unwrap();
+
+            // Only the file's own invalid bytes are written back as bytes
+            buffer.insert(2, "\u{10FDFF}");
+            assert_eq!(buffer.invalid_byte_sequences(), 0);
+            buffer.save_to_file(&file_path).unwrap();
+            assert_eq!(
+                std::fs::read(&file_path).unwrap(),
+                "ok\u{10FDFF}\n".as_bytes()
+            );
+        }
+    }
 }
 
 #[cfg(test)]
@@ -4123,6 +4731,49 @@ mod property_tests {
             prop_assert_eq!(buffer.get_all_text().unwrap(), expected_text);
         }
 
+        /// The line index kept up through edits answers the same as line
+        /// starts recomputed from the final text
+        #[test]
+        fn prop_line_index_matches_recomputed(operations in operation_strategy()) {
+            let mut buffer =
+                TextBuffer::from_bytes(b"initial\ntext\nwith\n\nseveral\nlines\n".to_vec());
+
+            for op in operations {
+                match op {
+                    Operation::Insert { offset, text } => {
+                        let offset = offset.min(buffer.total_bytes());
+                        buffer.insert_bytes(offset, text);
+                    }
+                    Operation::Delete { offset, bytes } => {
+                        if offset < buffer.total_bytes() {
+                            let bytes = bytes.min(buffer.total_bytes() - offset);
+                            buffer.delete_bytes(offset, bytes);
+                        }
+                    }
+                }
+            }
+
+            let text = buffer.get_all_text().unwrap();
+            let line_starts: Vec<usize> = std::iter::once(0)
+                .chain(text.iter().enumerate().filter(|(_, &b)| b == b'\n').map(|(i, _)| i + 1))
+                .collect();
+
+            prop_assert_eq!(buffer.line_count(), Some(line_starts.len()));
+            for (line, &start) in line_starts.iter().enumerate() {
+                prop_assert_eq!(buffer.line_start_offset(line), Some(start));
+            }
+            prop_assert_eq!(buffer.line_start_offset(line_starts.len()), None);
+            for offset in 0..=text.len() {
+                let line = line_starts.partition_point(|&start| start <= offset) - 1;
+                prop_assert_eq!(
+                    buffer.offset_to_position(offset),
+                    Some(Position { line, column: offset - line_starts[line] }),
+                    "offset {}", offset
+                );
+                prop_assert_eq!(buffer.line_at_offset(offset), Some(line));
+            }
+        }
+
         #[test]
         fn prop_line_count_never_zero(operations in operation_strategy()) {
             let mut buffer = TextBuffer::from_bytes(b"test".to_vec());
@@ -4190,6 +4841,40 @@ mod property_tests {
                 }
             }
         }
+
+        #[test]
+        fn prop_windowed_saved_diff_matches_full_diff(
+            text in text_with_newlines(),
+            operations in operation_strategy(),
+            save_every in 0usize..10
+        ) {
+            let mut buffer = TextBuffer::from_bytes(text);
+
+            for (i, op) in operations.into_iter().enumerate() {
+                match op {
+                    Operation::Insert { offset, text } => {
+                        let offset = offset.min(buffer.total_bytes());
+                        buffer.insert_bytes(offset, text);
+                    }
+                    Operation::Delete { offset, bytes } => {
+                        buffer.delete_bytes(offset, bytes);
+                    }
+                }
+                if save_every > 0 && i % save_every == save_every - 1 {
+                    buffer.mark_saved_snapshot();
+                }
+
+                let windowed = buffer.diff_since_saved();
+                // Asking again is served from the cache
+                prop_assert_eq!(&buffer.diff_since_saved(), &windowed);
+
+                let dirty = buffer.saved_dirty;
+                buffer.saved_dirty = DirtyRegion::Unbounded;
+                let full = buffer.compute_diff_since_saved();
+                buffer.saved_dirty = dirty;
+                prop_assert_eq!(windowed, full);
+            }
+        }
     }
 
     #[test]
@@ -362,6 +362,26 @@ impl Cursors {
         }
     }
 
+    /// Remove cursors an edit left on top of another one, at the same
+    /// position with the same selection, keeping the primary
+    pub fn merge_duplicates(&mut self) {
+        let primary_id = self.primary_id;
+        let mut cursor_list: Vec<(CursorId, Cursor)> =
+            self.cursors.iter().map(|(id, c)| (*id, *c)).collect();
+        cursor_list.sort_by_key(|(id, c)| (c.position, c.anchor, *id != primary_id, id.0));
+        cursor_list.dedup_by(|(_, a), (_, b)| a.position == b.position && a.anchor == b.anchor);
+        if cursor_list.len() < self.cursors.len() {
+            self.cursors = cursor_list.into_iter().collect();
+        }
+    }
+
+    /// Make an existing cursor the primary one
+    pub fn set_primary(&mut self, id: CursorId) {
+        if self.cursors.contains_key(&id) {
+            self.primary_id = id;
+        }
+    }
+
     /// Get all cursor positions (for rendering)
     pub fn positions(&self) -> Vec<usize> {
         self.cursors.values().map(|c| c.position).collect()
@@ -374,6 +394,32 @@ impl Cursors {
             .filter_map(|c| c.selection_range())
             .collect()
     }
+
+    /// Check that the primary cursor exists and that every cursor and anchor
+    /// lies within a buffer of `buffer_len` bytes
+    pub fn check_invariants(&self, buffer_len: usize) -> Result<(), String> {
+        if !self.cursors.contains_key(&self.primary_id) {
+            return Err(format!(
+                "primary cursor {:?} is not in the cursor set",
+                self.primary_id
+            ));
+        }
+        for (id, cursor) in &self.cursors {
+            if cursor.position > buffer_len {
+                return Err(format!(
+                    "cursor {:?} at {} is past the end of the buffer ({} bytes)",
+                    id, cursor.position, buffer_len
+                ));
+            }
+            if let Some(anchor) = cursor.anchor.filter(|&a| a > buffer_len) {
+                return Err(format!(
+                    "anchor of cursor {:?} at {} is past the end of the buffer ({} bytes)",
+                    id, anchor, buffer_len
+                ));
+            }
+        }
+        Ok(())
+    }
 }
 
 impl Default for Cursors {
@@ -465,4 +511,32 @@ mod tests {
         cursors.normalize();
         assert_eq!(cursors.count(), 2); // Duplicates removed
     }
+
+    #[test]
+    fn test_cursors_merge_duplicates_keeps_primary() {
+        let mut cursors = Cursors::new();
+        let first = cursors.add(Cursor::new(10));
+        cursors.add(Cursor::with_selection(4, 10));
+        let primary = cursors.add(Cursor::new(10));
+
+        cursors.merge_duplicates();
+        assert_eq!(cursors.count(), 3);
+        assert_eq!(cursors.primary_id(), primary);
+        assert!(cursors.get(first).is_none());
+    }
+
+    #[test]
+    fn test_cursors_check_invariants() {
+        let mut cursors = Cursors::new();
+        let id = cursors.add(Cursor::with_selection(2, 8));
+        assert!(cursors.check_invariants(10).is_ok());
+
+        let err = cursors.check_invariants(5).unwrap_err();
+        assert!(err.contains(&format!("cursor {:?} at 8", id)), "{}", err);
+
+        cursors.get_mut(id).unwrap().position = 4;
+        cursors.get_mut(id).unwrap().anchor = Some(12);
+        let err = cursors.check_invariants(10).unwrap_err();
+        assert!(err.contains("anchor of cursor"), "{}", err);
+    }
 }
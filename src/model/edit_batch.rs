@@ -0,0 +1,336 @@
+//! Edits made at many places at once, such as typing with many cursors
+//!
+//! Every edit's position is in the buffer as it was before any of them.
+//! Applied from the last position to the first, no edit moves one still to
+//! come, so the whole batch goes into the buffer in one pass. A position maps
+//! through the batch just as if the edits were applied one at a time, but in
+//! O(log n): the edits that reach it are those at or before it, and how far
+//! they move it is summed up front.
+
+use std::collections::HashMap;
+
+use crate::model::cursor::Cursor;
+use crate::model::event::{CursorId, Event};
+
+/// One edit of a batch
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BatchEdit {
+    pub position: usize,
+    pub delete_len: usize,
+    pub text: String,
+    pub cursor_id: CursorId,
+}
+
+/// Where the events put a cursor, in the buffer as it is once the edits
+/// before `edits_after` are applied
+#[derive(Debug, Clone, Copy)]
+struct CursorTarget {
+    edits_after: usize,
+    position: usize,
+    anchor: Option<usize>,
+    sticky_column: usize,
+}
+
+/// The inserts and deletes of a list of events, ready to apply together
+#[derive(Debug, Default)]
+pub struct EditBatch {
+    /// The edits in the order they're applied, positions descending
+    edits: Vec<BatchEdit>,
+    /// `shifts[i]` is how far the edits from `i` on move a position they
+    /// all reach
+    shifts: Vec<isize>,
+    /// `floors[i]` is the lowest the edits from `i` on leave such a
+    /// position, since deleting the text around a position leaves it at the
+    /// start of the deletion
+    floors: Vec<Option<isize>>,
+    /// Where the events move the cursors they name
+    targets: HashMap<CursorId, CursorTarget>,
+}
+
+impl EditBatch {
+    /// Collect the inserts, deletes and cursor moves of `events`, the moves
+    /// giving where the cursors end up once all the edits are made
+    ///
+    /// Edits at the same position keep their order, so a cursor's selection
+    /// is deleted before the text replacing it goes in.
+    pub fn from_events(events: &[Event]) -> Self {
+        Self::collect(events, false)
+    }
+
+    /// Collect the events generated for each cursor of a multi-cursor action,
+    /// which would be applied in turn from the last cursor to the first
+    ///
+    /// A cursor's move lands in the text its own edits left, and moves on
+    /// with the edits of the cursors before it.
+    pub fn from_cursor_events(events: &[Event]) -> Self {
+        Self::collect(events, true)
+    }
+
+    fn collect(events: &[Event], moves_follow_edits: bool) -> Self {
+        let mut edits: Vec<(usize, BatchEdit)> = Vec::new();
+        for event in events {
+            let edit = match event {
+                Event::Insert {
+                    position,
+                    text,
+                    cursor_id,
+                } => BatchEdit {
+                    position: *position,
+                    delete_len: 0,
+                    text: text.clone(),
+                    cursor_id: *cursor_id,
+                },
+                Event::Delete {
+                    range, cursor_id, ..
+                } => BatchEdit {
+                    position: range.start,
+                    delete_len: range.len(),
+                    text: String::new(),
+                    cursor_id: *cursor_id,
+                },
+                _ => continue,
+            };
+            edits.push((edits.len(), edit));
+        }
+        edits.sort_by(|a, b| b.1.position.cmp(&a.1.position));
+
+        // Where each edit, counted in event order, ended up once sorted
+        let mut sorted_index = vec![0; edits.len()];
+        for (index, (event_index, _)) in edits.iter().enumerate() {
+            sorted_index[*event_index] = index;
+        }
+
+        let mut targets: HashMap<CursorId, CursorTarget> = HashMap::new();
+        let mut last_edit: HashMap<CursorId, usize> = HashMap::new();
+        let mut event_index = 0;
+        for event in events {
+            let (cursor_id, target) = match event {
+                Event::Insert {
+                    position,
+                    text,
+                    cursor_id,
+                } => {
+                    let index = sorted_index[event_index];
+                    event_index += 1;
+                    last_edit.insert(*cursor_id, index);
+                    (
+                        *cursor_id,
+                        CursorTarget {
+                            edits_after: index + 1,
+                            position: position + text.len(),
+                            anchor: None,
+                            sticky_column: 0,
+                        },
+                    )
+                }
+                Event::Delete {
+                    range, cursor_id, ..
+                } => {
+                    let index = sorted_index[event_index];
+                    event_index += 1;
+                    last_edit.insert(*cursor_id, index);
+                    (
+                        *cursor_id,
+                        CursorTarget {
+                            edits_after: index + 1,
+                            position: range.start,
+                            anchor: None,
+                            sticky_column: 0,
+                        },
+                    )
+                }
+                Event::MoveCursor {
+                    cursor_id,
+                    new_position,
+                    new_anchor,
+                    new_sticky_column,
+                    ..
+                } => {
+                    let edits_after = if moves_follow_edits {
+                        last_edit.get(cursor_id).map_or(0, |index| index + 1)
+                    } else {
+                        usize::MAX
+                    };
+                    (
+                        *cursor_id,
+                        CursorTarget {
+                            edits_after,
+                            position: *new_position,
+                            anchor: *new_anchor,
+                            sticky_column: *new_sticky_column,
+                        },
+                    )
+                }
+                _ => continue,
+            };
+            // A final move wins over the cursor's edits, whatever their order
+            if targets
+                .get(&cursor_id)
+                .is_some_and(|moved| moved.edits_after == usize::MAX)
+            {
+                continue;
+            }
+            targets.insert(cursor_id, target);
+        }
+
+        let edits: Vec<BatchEdit> = edits.into_iter().map(|(_, edit)| edit).collect();
+        let mut shifts = vec![0; edits.len() + 1];
+        let mut floors = vec![None; edits.len() + 1];
+        for (index, edit) in edits.iter().enumerate().rev() {
+            shifts[index] = shifts[index + 1] + edit.text.len() as isize - edit.delete_len as isize;
+            let floor = (edit.delete_len > 0)
+                .then(|| edit.position as isize + edit.text.len() as isize + shifts[index + 1]);
+            floors[index] = floor.max(floors[index + 1]);
+        }
+
+        Self {
+            edits,
+            shifts,
+            floors,
+            targets,
+        }
+    }
+
+    /// Whether the batch changes no text
+    pub fn is_empty(&self) -> bool {
+        self.edits.is_empty()
+    }
+
+    /// The edits in the order they're applied, positions descending
+    pub fn edits(&self) -> &[BatchEdit] {
+        &self.edits
+    }
+
+    /// The edits as `TextBuffer::apply_bulk_edits` takes them
+    pub fn bulk_edits(&self) -> Vec<(usize, usize, &str)> {
+        self.edits
+            .iter()
+            .map(|edit| (edit.position, edit.delete_len, edit.text.as_str()))
+            .collect()
+    }
+
+    /// Where a position ends up after the batch, moved on by text inserted
+    /// at it
+    pub fn map_position(&self, position: usize) -> usize {
+        self.map_from(position, 0)
+    }
+
+    /// Where a cursor ends up after the batch: where the events put it, or
+    /// moved with the text around it if they don't name it
+    pub fn map_cursor(&self, cursor_id: CursorId, cursor: &Cursor) -> (usize, Option<usize>) {
+        match self.targets.get(&cursor_id) {
+            Some(target) => (
+                self.map_from(target.position, target.edits_after),
+                target
+                    .anchor
+                    .map(|anchor| self.map_from(anchor, target.edits_after)),
+            ),
+            None => (
+                self.map_position(cursor.position),
+                cursor.anchor.map(|anchor| self.map_position(anchor)),
+            ),
+        }
+    }
+
+    /// The goal column the events give the cursor, if they name it
+    pub fn sticky_column(&self, cursor_id: CursorId) -> Option<usize> {
+        self.targets
+            .get(&cursor_id)
+            .map(|target| target.sticky_column)
+    }
+
+    /// Map a position through the edits from `first` on that reach it
+    fn map_from(&self, position: usize, first: usize) -> usize {
+        let first = first.max(self.edits.partition_point(|edit| edit.position > position));
+        if first >= self.edits.len() {
+            return position;
+        }
+        let shifted = position as isize + self.shifts[first];
+        shifted.max(self.floors[first].unwrap_or(0)).max(0) as usize
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn insert(position: usize, text: &str, cursor: usize) -> Event {
+        Event::Insert {
+            position,
+            text: text.to_string(),
+            cursor_id: CursorId(cursor),
+        }
+    }
+
+    fn delete(start: usize, end: usize, cursor: usize) -> Event {
+        Event::Delete {
+            range: start..end,
+            deleted_text: String::new(),
+            cursor_id: CursorId(cursor),
+        }
+    }
+
+    #[test]
+    fn test_positions_move_with_edits_at_or_before_them() {
+        let batch = EditBatch::from_events(&[insert(10, "ab", 1), insert(4, "x", 2)]);
+        assert_eq!(batch.map_position(0), 0);
+        assert_eq!(batch.map_position(4), 5);
+        assert_eq!(batch.map_position(9), 10);
+        assert_eq!(batch.map_position(10), 13);
+    }
+
+    #[test]
+    fn test_deleted_positions_go_to_the_start_of_the_deletion() {
+        let batch = EditBatch::from_events(&[delete(10, 14, 1), delete(2, 4, 2)]);
+        assert_eq!(batch.map_position(3), 2);
+        assert_eq!(batch.map_position(12), 8);
+        assert_eq!(batch.map_position(14), 8);
+        assert_eq!(batch.map_position(20), 14);
+    }
+
+    #[test]
+    fn test_cursors_follow_their_own_edits() {
+        // Replacing a selection, and an auto-closed bracket
+        let batch = EditBatch::from_cursor_events(&[
+            delete(10, 13, 1),
+            insert(10, "x", 1),
+            insert(4, "()", 2),
+            Event::MoveCursor {
+                cursor_id: CursorId(2),
+                old_position: 6,
+                new_position: 5,
+                old_anchor: None,
+                new_anchor: None,
+                old_sticky_column: 0,
+                new_sticky_column: 0,
+            },
+        ]);
+        assert_eq!(
+            batch.map_cursor(CursorId(1), &Cursor::with_selection(10, 13)),
+            (13, None)
+        );
+        assert_eq!(batch.map_cursor(CursorId(2), &Cursor::new(4)), (5, None));
+        assert_eq!(
+            batch.map_cursor(CursorId(3), &Cursor::with_selection(0, 20)),
+            (20, Some(0))
+        );
+    }
+
+    #[test]
+    fn test_moves_of_other_events_are_final() {
+        let batch = EditBatch::from_events(&[
+            insert(4, "()", 1),
+            Event::MoveCursor {
+                cursor_id: CursorId(1),
+                old_position: 4,
+                new_position: 6,
+                old_anchor: None,
+                new_anchor: Some(4),
+                old_sticky_column: 0,
+                new_sticky_column: 0,
+            },
+            insert(0, "x", 2),
+        ]);
+        assert_eq!(batch.map_cursor(CursorId(1), &Cursor::new(4)), (6, Some(4)));
+    }
+}
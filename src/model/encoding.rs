@@ -0,0 +1,471 @@
+//! Text encodings for files whose working-tree copy is not UTF-8
+//!
+//! Buffers always hold UTF-8. A file with a `working-tree-encoding`
+//! attribute is decoded when it is opened and encoded again when it is
+//! saved; only the UTF-16 family is supported, which covers what
+//! `.gitattributes` files use in practice.
+//!
+//! Bytes of a UTF-8 file that aren't valid UTF-8 are kept through editing
+//! as placeholder characters: byte `b` becomes U+10FD00 + `b`, a private use
+//! code point (invalid bytes are always 0x80 or above, so the placeholders
+//! are U+10FD80..=U+10FDFF). They render as `<XX>` like control characters,
+//! move and delete as one character, and are written back as the original
+//! byte when the file is saved. [`InvalidBytes`] records where the
+//! placeholders of the file are, so that only those are written back and a
+//! genuine U+10FDxx character typed later is saved as itself.
+
+use std::ops::Range;
+
+/// Encoding of a file on disk
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TextEncoding {
+    #[default]
+    Utf8,
+    Utf16 {
+        big_endian: bool,
+        /// Whether the file starts with a byte order mark
+        bom: bool,
+    },
+}
+
+impl TextEncoding {
+    /// Parse an encoding name as used by git (case-insensitive)
+    ///
+    /// Plain `UTF-16` means "with a byte order mark"; its endianness comes
+    /// from the mark when decoding and defaults to little-endian.
+    pub fn from_name(name: &str) -> Option<Self> {
+        let name = name.to_ascii_uppercase().replace('_', "-");
+        let encoding = match name.as_str() {
+            "UTF-8" | "UTF8" => TextEncoding::Utf8,
+            "UTF-16" | "UTF16" | "UTF-16LE-BOM" => TextEncoding::Utf16 {
+                big_endian: false,
+                bom: true,
+            },
+            "UTF-16BE-BOM" => TextEncoding::Utf16 {
+                big_endian: true,
+                bom: true,
+            },
+            "UTF-16LE" | "UTF16LE" => TextEncoding::Utf16 {
+                big_endian: false,
+                bom: false,
+            },
+            "UTF-16BE" | "UTF16BE" => TextEncoding::Utf16 {
+                big_endian: true,
+                bom: false,
+            },
+            _ => return None,
+        };
+        Some(encoding)
+    }
+
+    /// Whether saving in this encoding satisfies a request for `name`
+    ///
+    /// Plain `UTF-16` only asks for a byte order mark, so either endianness
+    /// does.
+    pub fn satisfies(self, name: &str) -> bool {
+        match Self::from_name(name) {
+            Some(requested) if requested == self => true,
+            Some(_) => {
+                matches!(name.to_ascii_uppercase().as_str(), "UTF-16" | "UTF16")
+                    && matches!(self, TextEncoding::Utf16 { bom: true, .. })
+            }
+            None => false,
+        }
+    }
+
+    /// Name shown to the user
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            TextEncoding::Utf8 => "UTF-8",
+            TextEncoding::Utf16 {
+                big_endian: false,
+                bom: false,
+            } => "UTF-16LE",
+            TextEncoding::Utf16 {
+                big_endian: true,
+                bom: false,
+            } => "UTF-16BE",
+            TextEncoding::Utf16 {
+                big_endian: false,
+                bom: true,
+            } => "UTF-16LE-BOM",
+            TextEncoding::Utf16 {
+                big_endian: true,
+                bom: true,
+            } => "UTF-16BE-BOM",
+        }
+    }
+
+    /// Decode file contents into UTF-8
+    ///
+    /// Returns the text together with the encoding to save it back with
+    /// (a byte order mark overrides the endianness), or None if the bytes
+    /// are not valid in this encoding.
+    pub fn decode(self, bytes: &[u8]) -> Option<(Vec<u8>, TextEncoding)> {
+        let TextEncoding::Utf16 {
+            mut big_endian,
+            bom,
+        } = self
+        else {
+            return std::str::from_utf8(bytes)
+                .ok()
+                .map(|_| (bytes.to_vec(), self));
+        };
+
+        let mut body = bytes;
+        match bytes {
+            [0xFF, 0xFE, rest @ ..] if bom => {
+                big_endian = false;
+                body = rest;
+            }
+            [0xFE, 0xFF, rest @ ..] if bom => {
+                big_endian = true;
+                body = rest;
+            }
+            _ => {}
+        }
+        if !body.len().is_multiple_of(2) {
+            return None;
+        }
+        let units: Vec<u16> = body
+            .chunks_exact(2)
+            .map(|pair| {
+                let pair = [pair[0], pair[1]];
+                if big_endian {
+                    u16::from_be_bytes(pair)
+                } else {
+                    u16::from_le_bytes(pair)
+                }
+            })
+            .collect();
+        let text = String::from_utf16(&units).ok()?;
+        Some((text.into_bytes(), TextEncoding::Utf16 { big_endian, bom }))
+    }
+
+    /// Encode UTF-8 buffer contents for writing to disk
+    ///
+    /// Invalid UTF-8 is replaced, which cannot happen for text that was
+    /// decoded or typed into the buffer.
+    pub fn encode(self, utf8: &[u8]) -> Vec<u8> {
+        let TextEncoding::Utf16 { big_endian, bom } = self else {
+            return utf8.to_vec();
+        };
+        let text = String::from_utf8_lossy(utf8);
+        let mut out = Vec::with_capacity(utf8.len() * 2 + 2);
+        let mut push = |unit: u16| {
+            if big_endian {
+                out.extend_from_slice(&unit.to_be_bytes());
+            } else {
+                out.extend_from_slice(&unit.to_le_bytes());
+            }
+        };
+        if bom {
+            push(0xFEFF);
+        }
+        for unit in text.encode_utf16() {
+            push(unit);
+        }
+        out
+    }
+}
+
+/// First code point of the placeholders for invalid bytes
+const PLACEHOLDER_BASE: u32 = 0x10FD00;
+
+/// Placeholder character standing for the invalid byte `byte`
+pub fn invalid_byte_placeholder(byte: u8) -> char {
+    char::from_u32(PLACEHOLDER_BASE + byte as u32).unwrap_or(char::REPLACEMENT_CHARACTER)
+}
+
+/// The invalid byte `ch` stands for, if it is a placeholder
+pub fn placeholder_byte(ch: char) -> Option<u8> {
+    let byte = (ch as u32).checked_sub(PLACEHOLDER_BASE)?;
+    (0x80..=0xFF).contains(&byte).then_some(byte as u8)
+}
+
+/// Whether the UTF-8 in `bytes` holds any placeholder
+pub fn contains_placeholders(bytes: &[u8]) -> bool {
+    // Placeholders all encode as F4 8F B6 xx or F4 8F B7 xx
+    bytes
+        .windows(3)
+        .any(|w| w[0] == 0xF4 && w[1] == 0x8F && (w[2] == 0xB6 || w[2] == 0xB7))
+        && String::from_utf8_lossy(bytes)
+            .chars()
+            .any(|ch| placeholder_byte(ch).is_some())
+}
+
+/// Replace the bytes of `bytes` that aren't valid UTF-8 with placeholders
+///
+/// Returns None when there is nothing to replace, and when the bytes already
+/// hold placeholder characters, since those couldn't be told apart from the
+/// replaced bytes on save.
+pub fn escape_invalid_utf8(bytes: &[u8]) -> Option<Vec<u8>> {
+    if std::str::from_utf8(bytes).is_ok() || contains_placeholders(bytes) {
+        return None;
+    }
+    let mut out = Vec::with_capacity(bytes.len() + 16);
+    for chunk in bytes.utf8_chunks() {
+        out.extend_from_slice(chunk.valid().as_bytes());
+        for &byte in chunk.invalid() {
+            let mut utf8 = [0; 4];
+            out.extend_from_slice(
+                invalid_byte_placeholder(byte)
+                    .encode_utf8(&mut utf8)
+                    .as_bytes(),
+            );
+        }
+    }
+    Some(out)
+}
+
+/// Length in bytes of a placeholder's UTF-8
+const PLACEHOLDER_LEN: usize = 4;
+
+/// Offsets of the placeholders in `text`, counted from `base`, with the
+/// bytes they stand for
+fn find_placeholders(text: &[u8], base: usize) -> Vec<(usize, u8)> {
+    let mut found = Vec::new();
+    if !contains_placeholders(text) {
+        return found;
+    }
+    let mut offset = base;
+    for chunk in text.utf8_chunks() {
+        for (i, ch) in chunk.valid().char_indices() {
+            if let Some(byte) = placeholder_byte(ch) {
+                found.push((offset + i, byte));
+            }
+        }
+        offset += chunk.valid().len() + chunk.invalid().len();
+    }
+    found
+}
+
+/// Where the placeholders for a file's invalid bytes are in its buffer
+///
+/// Moved along with edits the way markers are, so saving writes back just
+/// these and counting them doesn't need the text. A placeholder that an
+/// edit deletes or splits is dropped; one inserted again, as undo and
+/// pasting do, is recorded at its new place.
+#[derive(Debug, Clone, Default)]
+pub struct InvalidBytes {
+    /// Offset of each placeholder and the byte it stands for, in order
+    placeholders: Vec<(usize, u8)>,
+
+    /// Runs of adjacent placeholders, each one invalid sequence of the file
+    sequences: usize,
+
+    /// Placeholders dropped since the last [`InvalidBytes::take_dropped`]
+    dropped: usize,
+}
+
+impl InvalidBytes {
+    /// Record the placeholders of `text`, as escaped by
+    /// [`escape_invalid_utf8`]
+    pub fn scan(text: &[u8]) -> Self {
+        let mut invalid = Self {
+            placeholders: find_placeholders(text, 0),
+            ..Self::default()
+        };
+        invalid.recount();
+        invalid
+    }
+
+    /// Move the placeholders for `text` inserted at `offset`
+    pub fn insert(&mut self, offset: usize, text: &[u8]) {
+        let mut at = self.placeholders.partition_point(|&(pos, _)| pos < offset);
+        // Text inserted into a placeholder breaks it up
+        if at > 0 && self.placeholders[at - 1].0 + PLACEHOLDER_LEN > offset {
+            at -= 1;
+            self.placeholders.remove(at);
+            self.dropped += 1;
+        }
+        for placeholder in &mut self.placeholders[at..] {
+            placeholder.0 += text.len();
+        }
+        self.placeholders
+            .splice(at..at, find_placeholders(text, offset));
+        self.recount();
+    }
+
+    /// Move the placeholders for the bytes in `range` being deleted
+    pub fn delete(&mut self, range: Range<usize>) {
+        let first = self
+            .placeholders
+            .partition_point(|&(pos, _)| pos + PLACEHOLDER_LEN <= range.start);
+        let last = self
+            .placeholders
+            .partition_point(|&(pos, _)| pos < range.end);
+        if first < last {
+            self.dropped += last - first;
+            self.placeholders.drain(first..last);
+        }
+        for placeholder in &mut self.placeholders[first..] {
+            placeholder.0 -= range.len();
+        }
+        self.recount();
+    }
+
+    /// Record the placeholders again after the whole text was replaced by
+    /// `text`; only as many as there are fewer count as dropped
+    pub fn reset(&mut self, text: &[u8]) {
+        let before = self.placeholders.len();
+        self.placeholders = find_placeholders(text, 0);
+        self.dropped += before.saturating_sub(self.placeholders.len());
+        self.recount();
+    }
+
+    fn recount(&mut self) {
+        self.sequences = self
+            .placeholders
+            .iter()
+            .enumerate()
+            .filter(|&(i, &(pos, _))| {
+                i == 0 || self.placeholders[i - 1].0 + PLACEHOLDER_LEN != pos
+            })
+            .count();
+    }
+
+    /// Number of invalid bytes kept as placeholders
+    pub fn bytes(&self) -> usize {
+        self.placeholders.len()
+    }
+
+    /// Number of invalid byte sequences kept as placeholders
+    pub fn sequences(&self) -> usize {
+        self.sequences
+    }
+
+    /// Number of placeholders dropped since the last call
+    pub fn take_dropped(&mut self) -> usize {
+        std::mem::take(&mut self.dropped)
+    }
+
+    /// `text`, the whole buffer, with the recorded placeholders turned back
+    /// into the bytes they stand for
+    pub fn restore(&self, text: &[u8]) -> Vec<u8> {
+        let mut out = Vec::with_capacity(text.len());
+        let mut copied = 0;
+        for &(pos, byte) in &self.placeholders {
+            let mut utf8 = [0; PLACEHOLDER_LEN];
+            let placeholder = invalid_byte_placeholder(byte).encode_utf8(&mut utf8);
+            // Anything else there is kept as text rather than guessed at
+            if text.get(pos..pos + PLACEHOLDER_LEN) == Some(placeholder.as_bytes()) {
+                out.extend_from_slice(&text[copied..pos]);
+                out.push(byte);
+                copied = pos + PLACEHOLDER_LEN;
+            }
+        }
+        out.extend_from_slice(&text[copied..]);
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_names_follow_git() {
+        assert_eq!(
+            TextEncoding::from_name("utf-16le"),
+            Some(TextEncoding::Utf16 {
+                big_endian: false,
+                bom: false
+            })
+        );
+        assert_eq!(
+            TextEncoding::from_name("UTF-16"),
+            Some(TextEncoding::Utf16 {
+                big_endian: false,
+                bom: true
+            })
+        );
+        assert_eq!(TextEncoding::from_name("UTF8"), Some(TextEncoding::Utf8));
+        assert_eq!(TextEncoding::from_name("SHIFT-JIS"), None);
+
+        let big_endian_bom = TextEncoding::from_name("UTF-16BE-BOM").unwrap();
+        assert!(big_endian_bom.satisfies("utf-16"));
+        assert!(!big_endian_bom.satisfies("UTF-16LE-BOM"));
+        assert!(!TextEncoding::Utf8.satisfies("UTF-16"));
+    }
+
+    #[test]
+    fn test_utf16_round_trip() {
+        let text = "héllo\r\nwörld 🦀\n".as_bytes();
+        for name in ["UTF-16LE", "UTF-16BE", "UTF-16", "UTF-16BE-BOM"] {
+            let encoding = TextEncoding::from_name(name).unwrap();
+            let encoded = encoding.encode(text);
+            assert_eq!(encoding.decode(&encoded), Some((text.to_vec(), encoding)));
+        }
+    }
+
+    #[test]
+    fn test_byte_order_mark_sets_endianness() {
+        let encoded = TextEncoding::from_name("UTF-16BE-BOM")
+            .unwrap()
+            .encode(b"ab");
+        let (text, encoding) = TextEncoding::from_name("UTF-16")
+            .unwrap()
+            .decode(&encoded)
+            .unwrap();
+        assert_eq!(text, b"ab");
+        assert_eq!(encoding.display_name(), "UTF-16BE-BOM");
+        assert_eq!(
+            TextEncoding::from_name("UTF-16LE").unwrap().decode(b"a"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_invalid_bytes_round_trip_through_placeholders() {
+        let file = b"caf\xe9 ok\n\xff\xfe end \xc3\n";
+        let escaped = escape_invalid_utf8(file).unwrap();
+        let text = std::str::from_utf8(&escaped).unwrap();
+        assert_eq!(
+            text.chars()
+                .filter_map(placeholder_byte)
+                .collect::<Vec<_>>(),
+            [0xE9, 0xFF, 0xFE, 0xC3]
+        );
+        let invalid = InvalidBytes::scan(&escaped);
+        assert_eq!((invalid.bytes(), invalid.sequences()), (4, 3));
+        assert_eq!(invalid.restore(&escaped), file);
+        // Text that is already valid stays as it is
+        assert_eq!(escape_invalid_utf8("héllo".as_bytes()), None);
+    }
+
+    #[test]
+    fn test_invalid_bytes_follow_edits() {
+        let escaped = escape_invalid_utf8(b"\xff\xfe x \xc0").unwrap();
+        let mut invalid = InvalidBytes::scan(&escaped);
+        assert_eq!((invalid.bytes(), invalid.sequences()), (3, 2));
+
+        // Inserting inside the first placeholder drops it
+        let mut text = escaped.clone();
+        text.splice(1..1, *b"y");
+        invalid.insert(1, b"y");
+        assert_eq!(invalid.take_dropped(), 1);
+        assert_eq!((invalid.bytes(), invalid.sequences()), (2, 2));
+        assert_eq!(invalid.restore(&text)[5..], *b"\xfe x \xc0");
+
+        // Deleting a placeholder and putting it back, as undo does
+        let last = text.len() - 4;
+        let removed: Vec<u8> = text.drain(last..).collect();
+        invalid.delete(last..last + 4);
+        assert_eq!(invalid.take_dropped(), 1);
+        text.extend_from_slice(&removed);
+        invalid.insert(last, &removed);
+        assert_eq!(invalid.take_dropped(), 0);
+        assert_eq!(invalid.restore(&text)[5..], *b"\xfe x \xc0");
+    }
+
+    #[test]
+    fn test_literal_placeholders_disable_escaping() {
+        let mut file = invalid_byte_placeholder(0x90).to_string().into_bytes();
+        file.push(0xFF);
+        assert!(contains_placeholders(&file));
+        assert_eq!(escape_invalid_utf8(&file), None);
+        assert_eq!(placeholder_byte('a'), None);
+        assert_eq!(placeholder_byte(char::from_u32(0x10FD10).unwrap()), None);
+    }
+}
@@ -1,5 +1,6 @@
 use crate::model::piece_tree::PieceTree;
 use crate::view::overlay::{OverlayHandle, OverlayNamespace};
+use crate::view::theme::DecorationColor;
 use serde::{Deserialize, Serialize};
 use std::ops::Range;
 use std::sync::Arc;
@@ -256,8 +257,8 @@ pub enum OverlayFace {
     },
     /// Full style with multiple attributes
     Style {
-        color: (u8, u8, u8),
-        bg_color: Option<(u8, u8, u8)>,
+        color: DecorationColor,
+        bg_color: Option<DecorationColor>,
         bold: bool,
         italic: bool,
         underline: bool,
@@ -498,6 +499,35 @@ impl Event {
         }
     }
 
+    /// Rough heap size of the event's text and nested events
+    pub fn memory_bytes(&self) -> usize {
+        match self {
+            Event::Insert { text, .. } => text.capacity(),
+            Event::Delete { deleted_text, .. } => deleted_text.capacity(),
+            Event::Batch {
+                events,
+                description,
+            } => {
+                events
+                    .iter()
+                    .map(|e| std::mem::size_of::<Event>() + e.memory_bytes())
+                    .sum::<usize>()
+                    + description.capacity()
+            }
+            Event::BulkEdit {
+                old_cursors,
+                new_cursors,
+                description,
+                ..
+            } => {
+                (old_cursors.capacity() + new_cursors.capacity())
+                    * std::mem::size_of::<(CursorId, usize, Option<usize>)>()
+                    + description.capacity()
+            }
+            _ => 0,
+        }
+    }
+
     /// Returns the cursor ID associated with this event, if any
     pub fn cursor_id(&self) -> Option<CursorId> {
         match self {
@@ -511,6 +541,46 @@ impl Event {
     }
 }
 
+/// Who made a logged edit
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EditSource {
+    /// Typed or triggered by the user
+    #[default]
+    User,
+    /// Made by a plugin through the editing API
+    Plugin,
+}
+
+/// How undo and redo group plugin edits with user edits
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum UndoPolicy {
+    /// Every write is its own undo step, in the order it happened
+    #[default]
+    Chronological,
+    /// Plugin writes are undone and redone together with the user write they
+    /// followed, so each step covers exactly one user edit
+    SkipPluginEdits,
+}
+
+impl UndoPolicy {
+    pub fn from_config(undo_skips_plugin_edits: bool) -> Self {
+        if undo_skips_plugin_edits {
+            Self::SkipPluginEdits
+        } else {
+            Self::Chronological
+        }
+    }
+
+    /// Whether a write from `source` ends an undo/redo step
+    fn is_step_boundary(self, source: EditSource) -> bool {
+        match self {
+            Self::Chronological => true,
+            Self::SkipPluginEdits => source == EditSource::User,
+        }
+    }
+}
+
 /// A log entry containing an event and metadata
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LogEntry {
@@ -522,6 +592,15 @@ pub struct LogEntry {
 
     /// Optional description for debugging
     pub description: Option<String>,
+
+    /// Who made the edit
+    #[serde(default)]
+    pub source: EditSource,
+
+    /// Name of the edit in undo and redo messages, for edits made of many
+    /// changes such as a replace-all; fine-grained edits like typing have none
+    #[serde(default)]
+    pub label: Option<String>,
 }
 
 impl LogEntry {
@@ -533,13 +612,25 @@ impl LogEntry {
                 .unwrap()
                 .as_millis() as u64,
             description: None,
+            source: EditSource::User,
+            label: None,
         }
     }
 
+    pub fn with_source(mut self, source: EditSource) -> Self {
+        self.source = source;
+        self
+    }
+
     pub fn with_description(mut self, description: String) -> Self {
         self.description = Some(description);
         self
     }
+
+    pub fn with_label(mut self, label: String) -> Self {
+        self.label = Some(label);
+        self
+    }
 }
 
 /// Snapshot of editor state for fast undo/redo
@@ -576,6 +667,11 @@ pub struct EventLog {
     /// Index at which the buffer was last saved (for tracking modified status)
     /// When current_index equals saved_at_index, the buffer is not modified
     saved_at_index: Option<usize>,
+
+    /// Entries [`EventLog::truncate_history`] dropped from the front. The
+    /// indices the log hands out count them, so an index taken before a
+    /// truncation still means the same entry afterwards.
+    dropped: usize,
 }
 
 impl EventLog {
@@ -588,6 +684,7 @@ impl EventLog {
             snapshot_interval: 100,
             stream_file: None,
             saved_at_index: Some(0), // New buffer starts at "saved" state (index 0)
+            dropped: 0,
         }
     }
 
@@ -597,6 +694,15 @@ impl EventLog {
         self.saved_at_index = Some(self.current_index);
     }
 
+    /// Mark an earlier position as the saved point
+    /// Call this when a save of the contents at `index` completes after
+    /// more events were logged
+    pub fn mark_saved_at(&mut self, index: usize) {
+        self.saved_at_index = index
+            .checked_sub(self.dropped)
+            .map(|index| index.min(self.entries.len()));
+    }
+
     /// Check if the buffer is at the saved position (not modified)
     /// Returns true if we're at the saved position OR if all events between
     /// saved_at_index and current_index are readonly (don't modify buffer content)
@@ -621,8 +727,12 @@ impl EventLog {
         }
     }
 
-    /// Enable streaming events to a file
-    pub fn enable_streaming<P: AsRef<std::path::Path>>(&mut self, path: P) -> std::io::Result<()> {
+    /// Create the file events are streamed to and write its header
+    ///
+    /// The logs of all buffers and the editor's keystrokes go to clones of
+    /// the returned handle, which share its position, so lines are never
+    /// written over each other.
+    pub fn create_stream<P: AsRef<std::path::Path>>(path: P) -> std::io::Result<std::fs::File> {
         use std::io::Write;
 
         let mut file = std::fs::OpenOptions::new()
@@ -637,8 +747,12 @@ impl EventLog {
         writeln!(file, "# Format: JSON Lines (one event per line)")?;
         writeln!(file, "#")?;
 
+        Ok(file)
+    }
+
+    /// Enable streaming events to `file`, made by [`EventLog::create_stream`]
+    pub fn enable_streaming(&mut self, file: std::fs::File) {
         self.stream_file = Some(file);
-        Ok(())
     }
 
     /// Disable streaming
@@ -674,20 +788,26 @@ impl EventLog {
         }
     }
 
-    /// Log keystroke (for debugging)
-    pub fn log_keystroke(&mut self, key_code: &str, modifiers: &str) {
+    /// Log input latency for one event (for debugging)
+    pub fn log_input_latency(
+        &mut self,
+        kind: &str,
+        handle: std::time::Duration,
+        frame: std::time::Duration,
+    ) {
         if let Some(ref mut file) = self.stream_file {
             use std::io::Write;
 
-            let keystroke_info = serde_json::json!({
-                "type": "keystroke",
+            let latency_info = serde_json::json!({
+                "type": "latency",
                 "timestamp": chrono::Local::now().to_rfc3339(),
-                "key": key_code,
-                "modifiers": modifiers,
+                "event": kind,
+                "handle_us": handle.as_micros() as u64,
+                "frame_us": frame.as_micros() as u64,
             });
 
-            if let Err(e) = writeln!(file, "{keystroke_info}") {
-                tracing::trace!("Warning: Failed to write keystroke to stream: {e}");
+            if let Err(e) = writeln!(file, "{latency_info}") {
+                tracing::trace!("Warning: Failed to write latency to stream: {e}");
             }
             if let Err(e) = file.flush() {
                 tracing::trace!("Warning: Failed to flush event stream: {e}");
@@ -695,8 +815,24 @@ impl EventLog {
         }
     }
 
-    /// Append an event to the log
+    /// Append a user event to the log
     pub fn append(&mut self, event: Event) -> usize {
+        self.append_from(event, EditSource::User)
+    }
+
+    /// Append a user event to the log under `label`, the name undo and redo
+    /// report it by
+    pub fn append_labeled(&mut self, event: Event, label: String) -> usize {
+        self.append_entry(LogEntry::new(event).with_label(label))
+    }
+
+    /// Append an event made by `source` to the log
+    pub fn append_from(&mut self, event: Event, source: EditSource) -> usize {
+        self.append_entry(LogEntry::new(event).with_source(source))
+    }
+
+    /// Append an entry to the log, returning its index
+    pub fn append_entry(&mut self, entry: LogEntry) -> usize {
         // If we're not at the end, truncate future events
         if self.current_index < self.entries.len() {
             self.entries.truncate(self.current_index);
@@ -709,7 +845,8 @@ impl EventLog {
             let stream_entry = serde_json::json!({
                 "index": self.entries.len(),
                 "timestamp": chrono::Local::now().to_rfc3339(),
-                "event": event,
+                "event": entry.event,
+                "source": entry.source,
             });
 
             // Write JSON line and flush immediately for real-time logging
@@ -721,7 +858,6 @@ impl EventLog {
             }
         }
 
-        let entry = LogEntry::new(event);
         self.entries.push(entry);
         self.current_index = self.entries.len();
 
@@ -731,12 +867,12 @@ impl EventLog {
             // For now, just track that we'd create one here
         }
 
-        self.current_index - 1
+        self.dropped + self.current_index - 1
     }
 
     /// Get the current event index
     pub fn current_index(&self) -> usize {
-        self.current_index
+        self.dropped + self.current_index
     }
 
     /// Get the number of events in the log
@@ -758,16 +894,22 @@ impl EventLog {
     /// Collects all events up to and including the first write action, returns their inverses
     /// This processes readonly events (like scrolling) and stops at write events (like Insert/Delete)
     pub fn undo(&mut self) -> Vec<Event> {
+        self.undo_with(UndoPolicy::Chronological)
+    }
+
+    /// Move back one undo step as grouped by `policy`
+    pub fn undo_with(&mut self, policy: UndoPolicy) -> Vec<Event> {
         let mut inverse_events = Vec::new();
         let mut found_write_action = false;
 
-        // Keep moving backward until we find a write action
+        // Keep moving backward until we find a write action that ends the step
         while self.can_undo() && !found_write_action {
             self.current_index -= 1;
-            let event = &self.entries[self.current_index].event;
+            let entry = &self.entries[self.current_index];
+            let event = &entry.event;
 
             // Check if this is a write action - we'll stop after processing it
-            if event.is_write_action() {
+            if event.is_write_action() && policy.is_step_boundary(entry.source) {
                 found_write_action = true;
             }
 
@@ -781,19 +923,62 @@ impl EventLog {
         inverse_events
     }
 
+    /// Label of the step [`EventLog::undo_with`] would undo next: that of its
+    /// latest labeled entry, None if none has one
+    pub fn undo_label_with(&self, policy: UndoPolicy) -> Option<&str> {
+        let mut index = self.current_index;
+        while index > 0 {
+            index -= 1;
+            let entry = &self.entries[index];
+            if let Some(label) = &entry.label {
+                return Some(label);
+            }
+            if entry.event.is_write_action() && policy.is_step_boundary(entry.source) {
+                break;
+            }
+        }
+        None
+    }
+
+    /// Label of the step [`EventLog::redo_with`] would redo next: that of its
+    /// earliest labeled entry, None if none has one
+    pub fn redo_label_with(&self, policy: UndoPolicy) -> Option<&str> {
+        let mut found_write_action = false;
+        for entry in &self.entries[self.current_index..] {
+            let is_write_action = entry.event.is_write_action();
+            if found_write_action && is_write_action && policy.is_step_boundary(entry.source) {
+                break;
+            }
+            if let Some(label) = &entry.label {
+                return Some(label);
+            }
+            found_write_action |= is_write_action;
+        }
+        None
+    }
+
     /// Move forward through events (for redo)
     /// Collects the first write action plus all readonly events after it (until next write action)
     /// This processes readonly events (like scrolling) with write events (like Insert/Delete)
     pub fn redo(&mut self) -> Vec<Event> {
+        self.redo_with(UndoPolicy::Chronological)
+    }
+
+    /// Move forward one redo step as grouped by `policy`
+    pub fn redo_with(&mut self, policy: UndoPolicy) -> Vec<Event> {
         let mut events = Vec::new();
         let mut found_write_action = false;
 
         // Keep moving forward to collect write action and subsequent readonly events
         while self.can_redo() {
-            let event = self.entries[self.current_index].event.clone();
-
-            // If we've already found a write action and this is another write action, stop
-            if found_write_action && event.is_write_action() {
+            let entry = &self.entries[self.current_index];
+            let event = entry.event.clone();
+
+            // If we've already found a write action and this write starts the next step, stop
+            if found_write_action
+                && event.is_write_action()
+                && policy.is_step_boundary(entry.source)
+            {
                 // Don't include this event, it's the next write action
                 break;
             }
@@ -811,14 +996,45 @@ impl EventLog {
         events
     }
 
+    /// Forget the redo history from `index` on and move there
+    ///
+    /// Used when entries that were undone no longer apply to the buffer.
+    pub fn discard_from(&mut self, index: usize) {
+        let index = index.saturating_sub(self.dropped);
+        self.entries.truncate(index);
+        self.current_index = self.current_index.min(index);
+        if self.saved_at_index.is_some_and(|saved| saved > index) {
+            self.saved_at_index = None;
+        }
+    }
+
+    /// Replace the entries from `index` up to the current position with one
+    /// entry holding `event` and labeled `label`, so they undo as a single
+    /// step
+    ///
+    /// `event` must take the buffer from its state at `index` to its current
+    /// one, e.g. a [`Event::BulkEdit`] between the two trees.
+    pub fn squash_from(&mut self, index: usize, event: Event, label: String) {
+        let index = index.saturating_sub(self.dropped).min(self.current_index);
+        self.entries.truncate(index);
+        self.current_index = index;
+        if self.saved_at_index.is_some_and(|saved| saved > index) {
+            self.saved_at_index = None;
+        }
+        self.append_labeled(event, label);
+    }
+
     /// Get all events from the log
     pub fn entries(&self) -> &[LogEntry] {
         &self.entries
     }
 
-    /// Get events in a range
+    /// Get events in a range of indices, without those dropped by
+    /// [`EventLog::truncate_history`]
     pub fn range(&self, range: Range<usize>) -> &[LogEntry] {
-        &self.entries[range]
+        let start = range.start.saturating_sub(self.dropped);
+        let end = range.end.saturating_sub(self.dropped).max(start);
+        &self.entries[start..end]
     }
 
     /// Get the most recent event
@@ -837,6 +1053,87 @@ impl EventLog {
         self.snapshots.clear();
     }
 
+    /// Forget all but the last `keep` undo steps before the current
+    /// position, returning how many entries were dropped
+    ///
+    /// The redo steps stay. When the saved point is among the dropped
+    /// entries, the buffer counts as modified from then on unless it is
+    /// at the saved state now.
+    pub fn truncate_history(&mut self, keep: usize) -> usize {
+        // Walk back over `keep` steps; each begins at a write action, as
+        // undo counts them
+        let mut start = self.current_index;
+        let mut steps = 0;
+        while start > 0 && steps < keep {
+            start -= 1;
+            if self.entries[start].event.is_write_action() {
+                steps += 1;
+            }
+        }
+        if start == 0 {
+            return 0;
+        }
+
+        let at_saved = self.is_at_saved_position();
+        self.entries.drain(..start);
+        self.entries.shrink_to_fit();
+        self.current_index -= start;
+        self.dropped += start;
+        self.snapshots.clear();
+        self.saved_at_index = match self.saved_at_index {
+            Some(saved) if saved >= start => Some(saved - start),
+            _ if at_saved => Some(self.current_index),
+            _ => None,
+        };
+        start
+    }
+
+    /// Forget every undo and redo step, returning how many entries were
+    /// dropped
+    pub fn discard_history(&mut self) -> usize {
+        let redo = self.entries.len() - self.current_index;
+        self.discard_from(self.current_index());
+        let dropped = self.truncate_history(0);
+        self.entries.shrink_to_fit();
+        redo + dropped
+    }
+
+    /// Estimated heap memory used by the undo and redo steps, not counting
+    /// the piece trees they keep
+    pub fn memory_bytes(&self) -> usize {
+        self.entries.capacity() * std::mem::size_of::<LogEntry>()
+            + self
+                .entries
+                .iter()
+                .map(|entry| {
+                    entry.event.memory_bytes()
+                        + entry.description.as_ref().map_or(0, String::capacity)
+                        + entry.label.as_ref().map_or(0, String::capacity)
+                })
+                .sum::<usize>()
+    }
+
+    /// Piece trees the undo and redo steps keep, whose text has to stay
+    /// readable
+    pub fn piece_trees(&self) -> Vec<Arc<PieceTree>> {
+        fn collect(event: &Event, trees: &mut Vec<Arc<PieceTree>>) {
+            match event {
+                Event::BulkEdit {
+                    old_tree, new_tree, ..
+                } => trees.extend(old_tree.iter().chain(new_tree.iter()).cloned()),
+                Event::Batch { events, .. } => {
+                    events.iter().for_each(|event| collect(event, trees))
+                }
+                _ => {}
+            }
+        }
+        let mut trees = Vec::new();
+        for entry in &self.entries {
+            collect(&entry.event, &mut trees);
+        }
+        trees
+    }
+
     /// Save event log to JSON Lines format
     pub fn save_to_file(&self, path: &std::path::Path) -> std::io::Result<()> {
         use std::io::Write;
@@ -913,6 +1210,17 @@ mod tests {
             ]
         }
 
+        /// A user or plugin edit; positions are reduced modulo the text length
+        fn arb_sourced_edit() -> impl Strategy<Value = (EditSource, bool, usize, usize, String)> {
+            (
+                prop_oneof![Just(EditSource::User), Just(EditSource::Plugin)],
+                any::<bool>(),
+                any::<usize>(),
+                any::<usize>(),
+                "[a-z]{1,5}",
+            )
+        }
+
         proptest! {
             /// Event inverse should be truly inverse
             #[test]
@@ -971,6 +1279,55 @@ mod tests {
                 assert_eq!(redo_count, events.len());
             }
 
+            /// Exhaustive undo restores the original text for any interleaving
+            /// of user and plugin edits, under either policy, and redo
+            /// brings the edits back
+            #[test]
+            fn undo_round_trips_interleaved_edits(
+                edits in prop::collection::vec(arb_sourced_edit(), 1..30),
+                skip_plugin_edits in any::<bool>(),
+            ) {
+                let original = "hello world".to_string();
+                let mut text = original.clone();
+                let mut log = EventLog::new();
+
+                for (source, insert, a, b, inserted) in edits {
+                    let event = if insert || text.is_empty() {
+                        Event::Insert {
+                            position: a % (text.len() + 1),
+                            text: inserted,
+                            cursor_id: CursorId(0),
+                        }
+                    } else {
+                        let start = a % text.len();
+                        let end = start + 1 + b % (text.len() - start);
+                        Event::Delete {
+                            range: start..end,
+                            deleted_text: text[start..end].to_string(),
+                            cursor_id: CursorId(0),
+                        }
+                    };
+                    apply_to_text(&mut text, &event);
+                    log.append_from(event, source);
+                }
+                let edited = text.clone();
+
+                let policy = UndoPolicy::from_config(skip_plugin_edits);
+                while log.can_undo() {
+                    for event in log.undo_with(policy) {
+                        apply_to_text(&mut text, &event);
+                    }
+                }
+                prop_assert_eq!(&text, &original);
+
+                while log.can_redo() {
+                    for event in log.redo_with(policy) {
+                        apply_to_text(&mut text, &event);
+                    }
+                }
+                prop_assert_eq!(text, edited);
+            }
+
             /// Appending after undo should truncate redo history
             #[test]
             fn append_after_undo_truncates(
@@ -997,6 +1354,294 @@ mod tests {
         }
     }
 
+    /// Apply an insert, delete or batch of them to plain text
+    fn apply_to_text(text: &mut String, event: &Event) {
+        match event {
+            Event::Insert {
+                position,
+                text: inserted,
+                ..
+            } => text.insert_str(*position, inserted),
+            Event::Delete { range, .. } => text.replace_range(range.clone(), ""),
+            Event::Batch { events, .. } => {
+                for event in events {
+                    apply_to_text(text, event);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn log_with_plugin_edit() -> (EventLog, String) {
+        let mut log = EventLog::new();
+        let mut text = String::new();
+        for (event, source) in [
+            (
+                Event::Insert {
+                    position: 0,
+                    text: "a".to_string(),
+                    cursor_id: CursorId(0),
+                },
+                EditSource::User,
+            ),
+            (
+                Event::Insert {
+                    position: 1,
+                    text: "b".to_string(),
+                    cursor_id: CursorId(0),
+                },
+                EditSource::User,
+            ),
+            (
+                Event::Insert {
+                    position: 0,
+                    text: "X".to_string(),
+                    cursor_id: CursorId(0),
+                },
+                EditSource::Plugin,
+            ),
+        ] {
+            apply_to_text(&mut text, &event);
+            log.append_from(event, source);
+        }
+        (log, text)
+    }
+
+    #[test]
+    fn test_chronological_undo_reverts_plugin_edit_first() {
+        let (mut log, mut text) = log_with_plugin_edit();
+        assert_eq!(text, "Xab");
+        for event in log.undo_with(UndoPolicy::Chronological) {
+            apply_to_text(&mut text, &event);
+        }
+        assert_eq!(text, "ab");
+    }
+
+    #[test]
+    fn test_skipping_undo_reverts_last_user_edit() {
+        let (mut log, mut text) = log_with_plugin_edit();
+        for event in log.undo_with(UndoPolicy::SkipPluginEdits) {
+            apply_to_text(&mut text, &event);
+        }
+        // The plugin edit goes together with the keystroke it followed
+        assert_eq!(text, "a");
+        assert_eq!(log.current_index(), 1);
+
+        for event in log.redo_with(UndoPolicy::SkipPluginEdits) {
+            apply_to_text(&mut text, &event);
+        }
+        assert_eq!(text, "Xab");
+        assert!(!log.can_redo());
+        assert_eq!(log.entries()[2].source, EditSource::Plugin);
+    }
+
+    #[test]
+    fn test_discard_from_drops_redo_history() {
+        let (mut log, _) = log_with_plugin_edit();
+        log.undo();
+        log.undo();
+        log.discard_from(1);
+        assert_eq!(log.len(), 1);
+        assert!(!log.can_redo());
+        assert!(log.can_undo());
+    }
+
+    #[test]
+    fn test_squash_from_undoes_as_one_step() {
+        let (mut log, mut text) = log_with_plugin_edit();
+        log.squash_from(
+            1,
+            Event::Batch {
+                events: vec![
+                    Event::Insert {
+                        position: 1,
+                        text: "b".to_string(),
+                        cursor_id: CursorId(0),
+                    },
+                    Event::Insert {
+                        position: 0,
+                        text: "X".to_string(),
+                        cursor_id: CursorId(0),
+                    },
+                ],
+                description: "squashed".to_string(),
+            },
+            "Squashed".to_string(),
+        );
+        assert_eq!(log.len(), 2);
+        assert_eq!(log.current_index(), 2);
+        assert_eq!(
+            log.undo_label_with(UndoPolicy::Chronological),
+            Some("Squashed")
+        );
+
+        for event in log.undo() {
+            apply_to_text(&mut text, &event);
+        }
+        assert_eq!(text, "a");
+        assert_eq!(log.current_index(), 1);
+    }
+
+    #[test]
+    fn test_labels_name_their_undo_step() {
+        let mut log = EventLog::new();
+        let policy = UndoPolicy::Chronological;
+        log.append(Event::Insert {
+            position: 0,
+            text: "a".to_string(),
+            cursor_id: CursorId(0),
+        });
+        log.append_labeled(
+            Event::Insert {
+                position: 1,
+                text: "bc".to_string(),
+                cursor_id: CursorId(0),
+            },
+            "Paste 1 line(s)".to_string(),
+        );
+        assert_eq!(log.undo_label_with(policy), Some("Paste 1 line(s)"));
+        assert_eq!(log.redo_label_with(policy), None);
+
+        log.undo();
+        // Typing stays unlabeled
+        assert_eq!(log.undo_label_with(policy), None);
+        assert_eq!(log.redo_label_with(policy), Some("Paste 1 line(s)"));
+
+        log.undo();
+        assert_eq!(log.redo_label_with(policy), None);
+        log.redo();
+        assert_eq!(log.redo_label_with(policy), Some("Paste 1 line(s)"));
+    }
+
+    #[test]
+    fn test_label_covers_plugin_edits_undone_with_it() {
+        let mut log = EventLog::new();
+        log.append_labeled(
+            Event::Insert {
+                position: 0,
+                text: "ab".to_string(),
+                cursor_id: CursorId(0),
+            },
+            "Paste 1 line(s)".to_string(),
+        );
+        log.append_from(
+            Event::Insert {
+                position: 0,
+                text: "X".to_string(),
+                cursor_id: CursorId(0),
+            },
+            EditSource::Plugin,
+        );
+
+        assert_eq!(
+            log.undo_label_with(UndoPolicy::SkipPluginEdits),
+            Some("Paste 1 line(s)")
+        );
+        // Undone on its own, the plugin edit has no label
+        assert_eq!(log.undo_label_with(UndoPolicy::Chronological), None);
+        log.undo_with(UndoPolicy::SkipPluginEdits);
+        assert_eq!(
+            log.redo_label_with(UndoPolicy::SkipPluginEdits),
+            Some("Paste 1 line(s)")
+        );
+    }
+
+    /// A log of typing "abcd" one letter at a time, with a cursor move
+    /// after each letter
+    fn typed_log() -> (EventLog, String) {
+        let mut log = EventLog::new();
+        let mut text = String::new();
+        for (i, letter) in ["a", "b", "c", "d"].into_iter().enumerate() {
+            let event = Event::Insert {
+                position: i,
+                text: letter.to_string(),
+                cursor_id: CursorId(0),
+            };
+            apply_to_text(&mut text, &event);
+            log.append(event);
+            log.append(Event::MoveCursor {
+                cursor_id: CursorId(0),
+                old_position: i,
+                new_position: i + 1,
+                old_anchor: None,
+                new_anchor: None,
+                old_sticky_column: 0,
+                new_sticky_column: 0,
+            });
+        }
+        (log, text)
+    }
+
+    #[test]
+    fn test_truncate_history_keeps_newest_steps() {
+        let (mut log, mut text) = typed_log();
+        for event in log.undo() {
+            apply_to_text(&mut text, &event);
+        }
+        assert_eq!(log.current_index(), 6);
+
+        // "a" goes, "b" and "c" stay undoable and "d" redoable
+        assert_eq!(log.truncate_history(2), 2);
+        assert_eq!(log.current_index(), 6);
+        for event in log.redo() {
+            apply_to_text(&mut text, &event);
+        }
+        assert_eq!(text, "abcd");
+        for _ in 0..3 {
+            for event in log.undo() {
+                apply_to_text(&mut text, &event);
+            }
+        }
+        assert_eq!(text, "a");
+        assert!(!log.can_undo());
+        assert_eq!(log.current_index(), 2);
+        assert_eq!(log.range(0..4).len(), 2);
+
+        // Nothing more to drop
+        assert_eq!(log.truncate_history(0), 0);
+    }
+
+    #[test]
+    fn test_truncate_history_keeps_saved_point() {
+        let (mut log, _) = typed_log();
+        log.mark_saved_at(6);
+
+        log.truncate_history(1);
+        assert!(!log.is_at_saved_position());
+        log.undo();
+        assert!(log.is_at_saved_position());
+
+        // With no undo steps left there's nothing to drop
+        log.truncate_history(0);
+        assert!(log.is_at_saved_position());
+
+        // Once dropped, the saved point and indices before it are gone
+        log.redo();
+        log.truncate_history(0);
+        assert!(!log.is_at_saved_position());
+        log.mark_saved_at(3);
+        assert!(!log.is_at_saved_position());
+    }
+
+    #[test]
+    fn test_discard_history_drops_undo_and_redo() {
+        let (mut log, _) = typed_log();
+        log.undo();
+        assert_eq!(log.discard_history(), 8);
+        assert!(!log.can_undo());
+        assert!(!log.can_redo());
+        assert!(log.entries().is_empty());
+        assert_eq!(log.current_index(), 6);
+
+        log.append(Event::Insert {
+            position: 3,
+            text: "e".to_string(),
+            cursor_id: CursorId(0),
+        });
+        assert_eq!(log.current_index(), 7);
+        assert_eq!(log.range(6..7).len(), 1);
+    }
+
     #[test]
     fn test_event_log_append() {
         let mut log = EventLog::new();
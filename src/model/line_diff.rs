@@ -275,6 +275,53 @@ fn classify_change(
     }
 }
 
+/// Largest LCS table `matching_lines` builds for the lines between the common
+/// prefix and suffix; bigger middles are left unmatched
+const MAX_MATCH_CELLS: usize = 4_000_000;
+
+/// Pairs of `(old line, new line)` indices of lines that stay the same between
+/// two texts, in ascending order.
+///
+/// Lines are split on `\n` as in [`diff_lines`]. Leading and trailing lines
+/// the texts share are matched directly; the lines between them go through the
+/// LCS only if the table stays under `MAX_MATCH_CELLS`, so a region that
+/// changed too much simply has no matches.
+pub fn matching_lines(old: &[u8], new: &[u8]) -> Vec<(usize, usize)> {
+    let old_lines: Vec<&[u8]> = old.split(|&b| b == b'\n').collect();
+    let new_lines: Vec<&[u8]> = new.split(|&b| b == b'\n').collect();
+
+    let prefix = old_lines
+        .iter()
+        .zip(&new_lines)
+        .take_while(|(a, b)| a == b)
+        .count();
+    let max_suffix = old_lines.len().min(new_lines.len()) - prefix;
+    let suffix = old_lines
+        .iter()
+        .rev()
+        .zip(new_lines.iter().rev())
+        .take(max_suffix)
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    let mut matches: Vec<(usize, usize)> = (0..prefix).map(|i| (i, i)).collect();
+
+    let old_middle = &old_lines[prefix..old_lines.len() - suffix];
+    let new_middle = &new_lines[prefix..new_lines.len() - suffix];
+    if old_middle.len().saturating_mul(new_middle.len()) <= MAX_MATCH_CELLS {
+        matches.extend(
+            longest_common_subsequence(old_middle, new_middle)
+                .into_iter()
+                .map(|m| (m.saved_idx + prefix, m.current_idx + prefix)),
+        );
+    }
+
+    let old_suffix_start = old_lines.len() - suffix;
+    let new_suffix_start = new_lines.len() - suffix;
+    matches.extend((0..suffix).map(|i| (old_suffix_start + i, new_suffix_start + i)));
+    matches
+}
+
 /// Merge adjacent or overlapping ranges.
 pub fn merge_ranges(ranges: Vec<Range<usize>>) -> Vec<Range<usize>> {
     if ranges.is_empty() {
@@ -303,6 +350,24 @@ pub fn merge_ranges(ranges: Vec<Range<usize>>) -> Vec<Range<usize>> {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_matching_lines() {
+        let old = b"a\nb\nc\nd\n";
+        let new = b"a\nx\nc\nd\ne\n";
+        assert_eq!(
+            matching_lines(old, new),
+            vec![(0, 0), (2, 2), (3, 3), (4, 5)]
+        );
+
+        let new = b"z\na\nb\nc\nd\n";
+        assert_eq!(
+            matching_lines(old, new),
+            vec![(0, 1), (1, 2), (2, 3), (3, 4), (4, 5)]
+        );
+
+        assert_eq!(matching_lines(b"", b""), vec![(0, 0)]);
+    }
+
     #[test]
     fn test_identical_content() {
         let content = b"line 1\nline 2\nline 3\n";
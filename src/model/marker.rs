@@ -12,6 +12,7 @@
 /// situations where marker count is low (<100).
 use std::collections::HashMap;
 
+use crate::model::edit_batch::EditBatch;
 use crate::model::marker_tree::IntervalTree;
 
 /// Unique identifier for a marker
@@ -167,6 +168,19 @@ impl MarkerList {
         self.tree.adjust_for_edit(position as u64, -(length as i64));
     }
 
+    /// Adjust all markers for edits made at many places at once
+    ///
+    /// A marker moves as it would with the edits adjusted for one at a time.
+    /// Cost: O(n log k) for n markers and k edits
+    pub fn adjust_for_edits(&mut self, batch: &EditBatch) {
+        if batch.is_empty() {
+            return;
+        }
+
+        self.tree
+            .remap(|position| batch.map_position(position as usize) as u64);
+    }
+
     /// Get the total size of the buffer (not directly tracked by IntervalTree)
     ///
     /// Note: This method is kept for API compatibility but is no longer used internally.
@@ -182,6 +196,18 @@ impl MarkerList {
         self._affinity_map.len()
     }
 
+    /// Estimated heap memory used by the markers
+    pub fn memory_bytes(&self) -> usize {
+        self.tree.memory_bytes()
+            + self._affinity_map.capacity() * (std::mem::size_of::<(MarkerId, bool)>() + 1)
+    }
+
+    /// Give back the capacity kept from markers since deleted
+    pub fn shrink_to_fit(&mut self) {
+        self.tree.shrink_to_fit();
+        self._affinity_map.shrink_to_fit();
+    }
+
     /// Set the initial buffer size (for tests)
     ///
     /// Note: This is a no-op in the IntervalTree implementation as buffer size
@@ -237,17 +237,39 @@ impl IntervalTree {
             return false;
         }
 
-        self.root = Self::delete_recursive(self.root.take(), start, id);
+        self.root = Self::delete_recursive(self.root.take(), start, id, &mut self.marker_map);
 
         self.marker_map.remove(&id).is_some()
     }
 
+    /// Estimated heap memory used by the nodes and the id map
+    pub fn memory_bytes(&self) -> usize {
+        use std::mem::size_of;
+        // Each node sits in an Rc allocation with its counts and RefCell flag
+        let nodes = self.marker_map.len() * (size_of::<RefCell<Node>>() + 2 * size_of::<usize>());
+        let map = self.marker_map.capacity() * (size_of::<(MarkerId, Rc<RefCell<Node>>)>() + 1);
+        nodes + map
+    }
+
+    /// Give back the capacity the id map kept from markers since deleted
+    pub fn shrink_to_fit(&mut self) {
+        self.marker_map.shrink_to_fit();
+    }
+
     /// Adjusts all markers for a text edit (insertion or deletion).
     /// Performance: O(log n) due to lazy delta propagation.
     pub fn adjust_for_edit(&mut self, pos: u64, delta: i64) {
         Self::adjust_recursive(&mut self.root, pos, delta);
     }
 
+    /// Moves every marker through `map`, for edits made at many places at
+    /// once. `map` must never move a position before a lower one.
+    /// Performance: O(n) calls of `map`, where a deletion adjusted with
+    /// `adjust_for_edit` already visits every marker after it.
+    pub fn remap(&mut self, map: impl Fn(u64) -> u64) {
+        Self::remap_recursive(&self.root, &map);
+    }
+
     /// Finds all markers that overlap a given query range.
     /// Performance: O(log n + k)
     pub fn query(&self, query_start: u64, query_end: u64) -> Vec<Marker> {
@@ -327,7 +349,12 @@ impl IntervalTree {
     }
 
     /// Recursive helper for delete
-    fn delete_recursive(root: NodePtr, start: u64, id: MarkerId) -> NodePtr {
+    fn delete_recursive(
+        root: NodePtr,
+        start: u64,
+        id: MarkerId,
+        marker_map: &mut HashMap<MarkerId, Rc<RefCell<Node>>>,
+    ) -> NodePtr {
         // Remove unnecessary 'mut'
         let root = match root {
             Some(r) => r,
@@ -341,20 +368,23 @@ impl IntervalTree {
 
         match start.cmp(&root_start) {
             Ordering::Less => {
-                root_mut.left = Self::delete_recursive(root_mut.left.take(), start, id);
+                root_mut.left = Self::delete_recursive(root_mut.left.take(), start, id, marker_map);
             }
             Ordering::Greater => {
-                root_mut.right = Self::delete_recursive(root_mut.right.take(), start, id);
+                root_mut.right =
+                    Self::delete_recursive(root_mut.right.take(), start, id, marker_map);
             }
             Ordering::Equal => match id.cmp(&root_id) {
                 Ordering::Less => {
-                    root_mut.left = Self::delete_recursive(root_mut.left.take(), start, id);
+                    root_mut.left =
+                        Self::delete_recursive(root_mut.left.take(), start, id, marker_map);
                 }
                 Ordering::Greater => {
-                    root_mut.right = Self::delete_recursive(root_mut.right.take(), start, id);
+                    root_mut.right =
+                        Self::delete_recursive(root_mut.right.take(), start, id, marker_map);
                 }
                 Ordering::Equal => {
-                    return Self::perform_node_deletion(root_mut, Rc::clone(&root));
+                    return Self::perform_node_deletion(root_mut, Rc::clone(&root), marker_map);
                 }
             },
         }
@@ -365,7 +395,11 @@ impl IntervalTree {
     }
 
     /// Handles the actual structural changes for deletion.
-    fn perform_node_deletion(mut node: RefMut<Node>, node_rc: Rc<RefCell<Node>>) -> NodePtr {
+    fn perform_node_deletion(
+        mut node: RefMut<Node>,
+        node_rc: Rc<RefCell<Node>>,
+        marker_map: &mut HashMap<MarkerId, Rc<RefCell<Node>>>,
+    ) -> NodePtr {
         if node.left.is_none() {
             let right = node.right.take();
             if let Some(ref r) = right {
@@ -381,14 +415,17 @@ impl IntervalTree {
         } else {
             let successor_rc = Self::min_node(&node.right.as_ref().unwrap());
 
-            let (successor_start, successor_id) = {
+            // The successor's marker moves into this node, so its map entry
+            // must follow it; the deleted marker moves into the successor's
+            // node, which is then the leftmost of the right subtree
+            mem::swap(&mut node.marker, &mut successor_rc.borrow_mut().marker);
+            marker_map.insert(node.marker.id, Rc::clone(&node_rc));
+            let (start, id) = {
                 let s = successor_rc.borrow();
                 (s.marker.interval.start, s.marker.id)
             };
 
-            mem::swap(&mut node.marker, &mut successor_rc.borrow_mut().marker);
-
-            node.right = Self::delete_recursive(node.right.take(), successor_start, successor_id);
+            node.right = Self::delete_recursive(node.right.take(), start, id, marker_map);
 
             drop(node);
             Node::update_stats(&node_rc);
@@ -471,6 +508,24 @@ impl IntervalTree {
         Node::update_stats(node_rc);
     }
 
+    /// Recursive helper for remap
+    fn remap_recursive(node_opt: &NodePtr, map: &impl Fn(u64) -> u64) {
+        let Some(node_rc) = node_opt else {
+            return;
+        };
+
+        Node::push_delta(node_rc);
+        {
+            let mut node = node_rc.borrow_mut();
+            let start = map(node.marker.interval.start);
+            node.marker.interval.start = start;
+            node.marker.interval.end = map(node.marker.interval.end).max(start);
+            Self::remap_recursive(&node.left, map);
+            Self::remap_recursive(&node.right, map);
+        }
+        Node::update_stats(node_rc);
+    }
+
     /// Recursive helper for query
     fn query_recursive(
         node_opt: &NodePtr,
@@ -877,4 +932,24 @@ mod tests {
             "Marker at 40 should shift to 24"
         );
     }
+
+    #[test]
+    fn test_delete_node_with_two_children_keeps_successor_tracked() {
+        let mut tree = IntervalTree::new();
+        let ids: Vec<MarkerId> = (0..7)
+            .map(|i| insert_marker(&mut tree, i * 10, i * 10 + 5))
+            .collect();
+
+        // Deleting an inner node moves its successor's marker into it
+        for &id in &ids[1..4] {
+            assert!(tree.delete(id));
+        }
+        tree.adjust_for_edit(0, 100);
+
+        for (i, &id) in ids.iter().enumerate().filter(|(i, _)| !(1..4).contains(i)) {
+            let start = i as u64 * 10 + 100;
+            assert_eq!(get_pos(&tree, id), (start, start + 5));
+        }
+        assert_eq!(tree.query(0, 1000).len(), 4);
+    }
 }
@@ -7,9 +7,13 @@ pub mod control_event;
 pub mod cursor;
 pub mod document_model;
 pub mod edit;
+pub mod edit_batch;
+pub mod encoding;
 pub mod event;
 pub mod line_diff;
 pub mod marker;
 pub mod marker_tree;
+pub mod pending_edits;
 pub mod piece_tree;
 pub mod piece_tree_diff;
+pub mod position_encoding;
@@ -0,0 +1,251 @@
+//! Pending edits awaiting user review
+//!
+//! A `PendingEditSet` holds a batch of proposed edits (e.g., from a project-wide
+//! replace or a multi-file code action) and tracks which of them are enabled.
+//! It is UI-agnostic: the review buffer in `app::pending_edits` renders the set
+//! and forwards the user's toggles back to it.
+
+use serde::{Deserialize, Serialize};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+/// A single proposed edit to a file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PendingEdit {
+    /// File the edit applies to
+    pub file: PathBuf,
+    /// Byte range in the file that will be replaced
+    pub range: Range<usize>,
+    /// Text currently in `range` (used to detect stale edits before applying)
+    pub old_text: String,
+    /// Replacement text
+    pub new_text: String,
+    /// Whether the edit will be applied
+    pub enabled: bool,
+}
+
+/// Enabled/total counts for a pending edit set
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct PendingEditCounts {
+    pub enabled_edits: usize,
+    pub total_edits: usize,
+    pub enabled_files: usize,
+    pub total_files: usize,
+}
+
+/// A batch of pending edits grouped by file
+///
+/// Files keep the order in which they first appear in the input; edits within
+/// a file are ordered by position.
+#[derive(Debug, Clone, Default)]
+pub struct PendingEditSet {
+    edits: Vec<PendingEdit>,
+    /// Files in display order, each with the indices of its edits
+    files: Vec<(PathBuf, Vec<usize>)>,
+}
+
+impl PendingEditSet {
+    /// Create a set from a list of edits
+    pub fn new(edits: Vec<PendingEdit>) -> Self {
+        let mut files: Vec<(PathBuf, Vec<usize>)> = Vec::new();
+        for (index, edit) in edits.iter().enumerate() {
+            match files.iter_mut().find(|(file, _)| *file == edit.file) {
+                Some((_, indices)) => indices.push(index),
+                None => files.push((edit.file.clone(), vec![index])),
+            }
+        }
+        for (_, indices) in &mut files {
+            indices.sort_by_key(|&i| (edits[i].range.start, edits[i].range.end));
+        }
+        Self { edits, files }
+    }
+
+    /// All edits, in input order
+    pub fn edits(&self) -> &[PendingEdit] {
+        &self.edits
+    }
+
+    /// Files in display order, with the indices of their edits
+    pub fn files(&self) -> &[(PathBuf, Vec<usize>)] {
+        &self.files
+    }
+
+    /// Check if the set has no edits
+    pub fn is_empty(&self) -> bool {
+        self.edits.is_empty()
+    }
+
+    /// Toggle a single edit, returning its new state
+    pub fn toggle(&mut self, index: usize) -> Option<bool> {
+        let edit = self.edits.get_mut(index)?;
+        edit.enabled = !edit.enabled;
+        Some(edit.enabled)
+    }
+
+    /// Toggle all edits in a file, returning the new state
+    ///
+    /// If any edit in the file is enabled, the whole file is excluded;
+    /// otherwise every edit in the file is enabled.
+    pub fn toggle_file(&mut self, file: &Path) -> Option<bool> {
+        let (_, indices) = self.files.iter().find(|(f, _)| f == file)?;
+        let enable = !indices.iter().any(|&i| self.edits[i].enabled);
+        for &i in indices {
+            self.edits[i].enabled = enable;
+        }
+        Some(enable)
+    }
+
+    /// Enable or disable every edit
+    pub fn set_all(&mut self, enabled: bool) {
+        for edit in &mut self.edits {
+            edit.enabled = enabled;
+        }
+    }
+
+    /// Enabled/total counts for a single file
+    pub fn file_counts(&self, file: &Path) -> (usize, usize) {
+        self.files
+            .iter()
+            .find(|(f, _)| f == file)
+            .map(|(_, indices)| {
+                let enabled = indices.iter().filter(|&&i| self.edits[i].enabled).count();
+                (enabled, indices.len())
+            })
+            .unwrap_or((0, 0))
+    }
+
+    /// Enabled/total counts for the whole set
+    pub fn counts(&self) -> PendingEditCounts {
+        let mut counts = PendingEditCounts {
+            total_edits: self.edits.len(),
+            total_files: self.files.len(),
+            ..Default::default()
+        };
+        for (_, indices) in &self.files {
+            let enabled = indices.iter().filter(|&&i| self.edits[i].enabled).count();
+            counts.enabled_edits += enabled;
+            if enabled > 0 {
+                counts.enabled_files += 1;
+            }
+        }
+        counts
+    }
+
+    /// The enabled edits, in input order
+    pub fn selected(&self) -> Vec<PendingEdit> {
+        self.edits.iter().filter(|e| e.enabled).cloned().collect()
+    }
+
+    /// The enabled edits grouped by file, each group ordered by position
+    pub fn selected_by_file(&self) -> Vec<(PathBuf, Vec<PendingEdit>)> {
+        self.files
+            .iter()
+            .filter_map(|(file, indices)| {
+                let edits: Vec<PendingEdit> = indices
+                    .iter()
+                    .map(|&i| &self.edits[i])
+                    .filter(|e| e.enabled)
+                    .cloned()
+                    .collect();
+                (!edits.is_empty()).then(|| (file.clone(), edits))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn edit(file: &str, start: usize, end: usize) -> PendingEdit {
+        PendingEdit {
+            file: PathBuf::from(file),
+            range: start..end,
+            old_text: "foo".to_string(),
+            new_text: "bar".to_string(),
+            enabled: true,
+        }
+    }
+
+    #[test]
+    fn test_groups_by_file_in_first_seen_order() {
+        let set = PendingEditSet::new(vec![
+            edit("b.rs", 20, 23),
+            edit("a.rs", 0, 3),
+            edit("b.rs", 4, 7),
+        ]);
+
+        let files: Vec<_> = set
+            .files()
+            .iter()
+            .map(|(f, i)| (f.clone(), i.clone()))
+            .collect();
+        assert_eq!(
+            files,
+            vec![
+                (PathBuf::from("b.rs"), vec![2, 0]),
+                (PathBuf::from("a.rs"), vec![1]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_counts_and_toggle() {
+        let mut set = PendingEditSet::new(vec![
+            edit("a.rs", 0, 3),
+            edit("a.rs", 10, 13),
+            edit("b.rs", 0, 3),
+        ]);
+        assert_eq!(
+            set.counts(),
+            PendingEditCounts {
+                enabled_edits: 3,
+                total_edits: 3,
+                enabled_files: 2,
+                total_files: 2,
+            }
+        );
+
+        assert_eq!(set.toggle(2), Some(false));
+        assert_eq!(set.toggle(5), None);
+        let counts = set.counts();
+        assert_eq!((counts.enabled_edits, counts.enabled_files), (2, 1));
+        assert_eq!(set.file_counts(Path::new("b.rs")), (0, 1));
+    }
+
+    #[test]
+    fn test_toggle_file_excludes_then_includes() {
+        let mut set = PendingEditSet::new(vec![edit("a.rs", 0, 3), edit("a.rs", 10, 13)]);
+        set.toggle(0);
+
+        // Partially enabled file is excluded entirely
+        assert_eq!(set.toggle_file(Path::new("a.rs")), Some(false));
+        assert_eq!(set.file_counts(Path::new("a.rs")), (0, 2));
+
+        // Fully excluded file is re-enabled entirely
+        assert_eq!(set.toggle_file(Path::new("a.rs")), Some(true));
+        assert_eq!(set.file_counts(Path::new("a.rs")), (2, 2));
+
+        assert_eq!(set.toggle_file(Path::new("missing.rs")), None);
+    }
+
+    #[test]
+    fn test_selected_by_file_skips_disabled() {
+        let mut set = PendingEditSet::new(vec![
+            edit("a.rs", 10, 13),
+            edit("b.rs", 0, 3),
+            edit("a.rs", 0, 3),
+        ]);
+        set.toggle(1);
+
+        let groups = set.selected_by_file();
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].0, PathBuf::from("a.rs"));
+        let ranges: Vec<_> = groups[0].1.iter().map(|e| e.range.clone()).collect();
+        assert_eq!(ranges, vec![0..3, 10..13]);
+
+        assert_eq!(set.selected().len(), 2);
+        set.set_all(false);
+        assert!(set.selected().is_empty());
+    }
+}
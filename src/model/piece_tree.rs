@@ -1,3 +1,4 @@
+use std::collections::HashSet;
 use std::io::{self, Read, Seek, SeekFrom};
 use std::path::PathBuf;
 use std::sync::Arc;
@@ -13,8 +14,11 @@ pub struct Position {
 #[derive(Debug, Clone)]
 pub enum BufferData {
     /// Loaded in memory with optional line indexing
+    ///
+    /// The bytes are shared so a snapshot for saving can hold on to them
+    /// without copying; appending copies them only while a snapshot exists.
     Loaded {
-        data: Vec<u8>,
+        data: Arc<Vec<u8>>,
         line_starts: Option<Vec<usize>>, // None = not indexed (large file mode)
     },
     /// Not yet loaded from file
@@ -43,7 +47,7 @@ impl StringBuffer {
         StringBuffer {
             id,
             data: BufferData::Loaded {
-                data,
+                data: Arc::new(data),
                 line_starts: Some(line_starts),
             },
         }
@@ -58,7 +62,10 @@ impl StringBuffer {
         };
         StringBuffer {
             id,
-            data: BufferData::Loaded { data, line_starts },
+            data: BufferData::Loaded {
+                data: Arc::new(data),
+                line_starts,
+            },
         }
     }
 
@@ -117,7 +124,7 @@ impl StringBuffer {
 
                 // Replace with loaded data (no line indexing for lazy-loaded chunks)
                 self.data = BufferData::Loaded {
-                    data: buffer,
+                    data: Arc::new(buffer),
                     line_starts: None,
                 };
 
@@ -187,6 +194,40 @@ impl StringBuffer {
         }
     }
 
+    /// Number of line feeds in `start..end` of this buffer
+    /// Binary searches the line starts when they're tracked, so splitting a
+    /// big piece doesn't rescan it; otherwise counts the loaded bytes.
+    /// Returns None if the buffer is unloaded
+    pub fn line_feeds_in(&self, start: usize, end: usize) -> Option<usize> {
+        match &self.data {
+            BufferData::Loaded {
+                line_starts: Some(line_starts),
+                ..
+            } => {
+                // A line feed at `i` starts a line at `i + 1`
+                let before = line_starts.partition_point(|&ls| ls <= start);
+                let through = line_starts.partition_point(|&ls| ls <= end);
+                Some(through.saturating_sub(before))
+            }
+            BufferData::Loaded { data, .. } => {
+                let end = end.min(data.len());
+                let start = start.min(end);
+                Some(data[start..end].iter().filter(|&&b| b == b'\n').count())
+            }
+            BufferData::Unloaded { .. } => None,
+        }
+    }
+
+    /// Start of the `n`th line (1-based) beginning strictly inside
+    /// `start..end` of this buffer, found by binary search
+    /// Returns None if there are fewer such lines or no line starts
+    fn nth_line_start_in(&self, start: usize, end: usize, n: usize) -> Option<usize> {
+        let line_starts = self.get_line_starts()?;
+        let first = line_starts.partition_point(|&ls| ls <= start);
+        let line_start = *line_starts.get(first + n.checked_sub(1)?)?;
+        (line_start < end).then_some(line_start)
+    }
+
     /// Append data to this buffer and recompute line starts
     /// Returns the offset where the appended data starts
     /// Only works for loaded buffers with line starts
@@ -194,7 +235,7 @@ impl StringBuffer {
         match &mut self.data {
             BufferData::Loaded { data, line_starts } => {
                 let start_offset = data.len();
-                data.extend_from_slice(data_to_append);
+                Arc::make_mut(data).extend_from_slice(data_to_append);
 
                 // Add new line starts if we're tracking them
                 if let Some(ref mut line_starts) = line_starts {
@@ -213,6 +254,30 @@ impl StringBuffer {
             }
         }
     }
+
+    /// Bytes the loaded data and its line starts take
+    pub fn memory_bytes(&self) -> usize {
+        match &self.data {
+            BufferData::Loaded { data, line_starts } => {
+                data.capacity()
+                    + line_starts
+                        .as_ref()
+                        .map_or(0, |starts| starts.capacity() * std::mem::size_of::<usize>())
+            }
+            BufferData::Unloaded { .. } => 0,
+        }
+    }
+
+    /// Drop the data of a buffer no piece refers to any more, keeping its
+    /// id in use
+    pub fn release(&mut self) {
+        if self.is_loaded() {
+            self.data = BufferData::Loaded {
+                data: Arc::new(Vec::new()),
+                line_starts: None,
+            };
+        }
+    }
 }
 
 /// Identifies which buffer a piece of text comes from
@@ -404,6 +469,23 @@ impl PieceTreeNode {
         }
     }
 
+    /// Add the ids of the buffers the subtree's pieces refer to to `ids`
+    pub fn collect_buffer_ids(&self, ids: &mut HashSet<usize>) {
+        match self {
+            PieceTreeNode::Internal { left, right, .. } => {
+                left.collect_buffer_ids(ids);
+                right.collect_buffer_ids(ids);
+            }
+            PieceTreeNode::Leaf {
+                location, bytes, ..
+            } => {
+                if *bytes > 0 {
+                    ids.insert(location.buffer_id());
+                }
+            }
+        }
+    }
+
     /// Collect all leaves in order
     fn collect_leaves(&self, leaves: &mut Vec<LeafData>) {
         match self {
@@ -575,7 +657,8 @@ impl PieceTreeNode {
                 // Get the buffer for this piece
                 let buffer_id = location.buffer_id();
                 let buffer = buffers.get(buffer_id)?;
-                let line_starts = buffer.get_line_starts()?;
+                // Line navigation needs the buffer's line starts
+                buffer.get_line_starts()?;
 
                 // Find the line within the piece
                 let line_in_piece = target_line - lines_before;
@@ -589,25 +672,12 @@ impl PieceTreeNode {
                     // First line starts at piece start
                     piece_start_in_buffer
                 } else {
-                    // Find the Nth newline within this piece
-                    // Count line_starts that fall within [piece_start, piece_end)
-                    let mut lines_seen = 0;
-                    let mut found_line_start = None;
-
-                    for &line_start in line_starts.iter() {
-                        // Line starts are positions of newlines + 1, or beginning of buffer (0)
-                        // We want line_starts that are > piece_start and < piece_end
-                        if line_start > piece_start_in_buffer && line_start < piece_end_in_buffer {
-                            if lines_seen == line_in_piece - 1 {
-                                // This is the start of our target line
-                                found_line_start = Some(line_start);
-                                break;
-                            }
-                            lines_seen += 1;
-                        }
-                    }
-
-                    found_line_start?
+                    // The Nth line starting inside this piece
+                    buffer.nth_line_start_in(
+                        piece_start_in_buffer,
+                        piece_end_in_buffer,
+                        line_in_piece,
+                    )?
                 };
 
                 // Add column offset
@@ -1142,19 +1212,9 @@ impl PieceTree {
         offset: usize,
         bytes: usize,
     ) -> Option<usize> {
-        let buffer_id = location.buffer_id();
-        if let Some(buffer) = buffers.get(buffer_id) {
-            if let Some(data) = buffer.get_data() {
-                let end = (offset + bytes).min(data.len());
-                Some(data[offset..end].iter().filter(|&&b| b == b'\n').count())
-            } else {
-                // Buffer is unloaded - return None
-                None
-            }
-        } else {
-            // Buffer not available - return None
-            None
-        }
+        buffers
+            .get(location.buffer_id())?
+            .line_feeds_in(offset, offset + bytes)
     }
 
     /// Split a piece at the given offset without inserting anything
@@ -1591,6 +1651,11 @@ impl PieceTree {
         }
     }
 
+    /// Add the ids of the buffers the tree's pieces refer to to `ids`
+    pub fn collect_buffer_ids(&self, ids: &mut HashSet<usize>) {
+        self.root.collect_buffer_ids(ids);
+    }
+
     /// Get all leaves in order (for debugging)
     pub fn get_leaves(&self) -> Vec<LeafData> {
         let mut leaves = Vec::new();
@@ -1660,21 +1725,9 @@ impl PieceTree {
                         let line_start = self.position_to_offset(doc_line, 0, buffers);
                         offset.saturating_sub(line_start)
                     } else {
-                        // Line starts within this piece
-                        // Find where the line starts within the piece
-                        let mut count = 0;
-                        let mut line_start_in_buf = piece_info.offset;
-                        for &ls in line_starts.iter() {
-                            if ls > piece_info.offset && ls < piece_info.offset + piece_info.bytes {
-                                count += 1;
-                                if count == line_in_piece {
-                                    line_start_in_buf = ls;
-                                    break;
-                                }
-                            }
-                        }
-                        let line_start_offset_in_piece = line_start_in_buf - piece_info.offset;
-                        offset_in_piece - line_start_offset_in_piece
+                        // Line starts within this piece, at the line start
+                        // the binary search above found
+                        byte_offset_in_buffer - line_starts[line_in_buffer]
                     };
 
                     return Some((doc_line, column));
@@ -1833,6 +1886,23 @@ impl PieceTree {
             edit_ranges.push((*pos, del_end, insert_leaf));
         }
 
+        // The deleted ranges in order, overlapping ones joined, so that each
+        // leaf is checked against one range rather than every edit
+        let mut deleted: Vec<(usize, usize)> = edit_ranges
+            .iter()
+            .filter(|(start, end, _)| end > start)
+            .map(|(start, end, _)| (*start, *end))
+            .collect();
+        deleted.sort_unstable();
+        let mut deleted_ranges: Vec<(usize, usize)> = Vec::with_capacity(deleted.len());
+        for (start, end) in deleted {
+            match deleted_ranges.last_mut() {
+                Some(last) if start <= last.1 => last.1 = last.1.max(end),
+                _ => deleted_ranges.push((start, end)),
+            }
+        }
+        let mut deleted_idx = 0;
+
         // 4. Apply edits to leaves
         // Edits are sorted descending by position, so:
         //   edit_ranges[len-1] has smallest position, edit_ranges[0] has largest
@@ -1864,33 +1934,15 @@ impl PieceTree {
                 edit_idx -= 1;
             }
 
-            // Check if this leaf overlaps with ANY edit's delete range
-            // We check ALL edits, not just remaining ones, because edits
-            // processed in the insert loop above may still have deletions
-            let mut keep_leaf = true;
-
-            for (edit_start, edit_end, _) in &edit_ranges {
-                // If edit's delete range is entirely after this leaf, skip
-                if *edit_start >= leaf_end {
-                    continue;
-                }
-
-                // If edit has no deletion (edit_start == edit_end), skip
-                if *edit_start == *edit_end {
-                    continue;
-                }
-
-                // If edit's delete range is entirely before this leaf, skip
-                if *edit_end <= leaf_start {
-                    continue;
-                }
-
-                // Leaf overlaps with this edit's delete range - filter it out
-                if leaf_start >= *edit_start && leaf_end <= *edit_end {
-                    keep_leaf = false;
-                    break;
-                }
+            // Leaves are split at both ends of every deletion, so a leaf is
+            // either wholly deleted or not at all
+            while deleted_idx < deleted_ranges.len() && deleted_ranges[deleted_idx].1 <= leaf_start
+            {
+                deleted_idx += 1;
             }
+            let keep_leaf = deleted_ranges
+                .get(deleted_idx)
+                .is_none_or(|(start, _)| *start > leaf_start);
 
             if keep_leaf {
                 new_leaves.push(leaf.clone());
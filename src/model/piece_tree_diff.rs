@@ -29,41 +29,175 @@ pub fn diff_piece_trees(
 ) -> PieceTreeDiff {
     let mut before_leaves = Vec::new();
     collect_leaves(before, &mut before_leaves);
-    let before_leaves = normalize_leaves(before_leaves);
 
     let mut after_leaves = Vec::new();
     collect_leaves(after, &mut after_leaves);
+
+    let Some((after_spans, ranges)) = diff_leaves(before_leaves, after_leaves) else {
+        return equal_diff();
+    };
+
+    // Map byte ranges to line ranges (best effort).
+    let line_ranges = line_ranges(&after_spans, &ranges, line_counter);
+
+    PieceTreeDiff {
+        equal: false,
+        byte_ranges: ranges,
+        line_ranges,
+    }
+}
+
+/// Compute a diff between two piece tree roots known to differ only inside
+/// `before_window` of `before` and `after_window` of `after`.
+///
+/// Both windows must start at the same offset, and everything past them must
+/// be the same pieces in both trees (see [`DirtyRegion`]). Only the leaves
+/// overlapping the windows are visited, so the cost follows the size of the
+/// edited region rather than the size of the document. Byte ranges match what
+/// [`diff_piece_trees`] reports for the whole trees; line ranges are left as
+/// `None` for the caller to fill in from the tree's own line metadata.
+pub fn diff_piece_tree_windows(
+    before: &Arc<PieceTreeNode>,
+    before_window: Range<usize>,
+    after: &Arc<PieceTreeNode>,
+    after_window: Range<usize>,
+) -> PieceTreeDiff {
+    debug_assert_eq!(before_window.start, after_window.start);
+    let start = after_window.start;
+
+    let mut before_leaves = Vec::new();
+    collect_leaves_in(before, 0, &before_window, &mut before_leaves);
+
+    let mut after_leaves = Vec::new();
+    collect_leaves_in(after, 0, &after_window, &mut after_leaves);
+
+    let Some((_, ranges)) = diff_leaves(before_leaves, after_leaves) else {
+        return equal_diff();
+    };
+
+    PieceTreeDiff {
+        equal: false,
+        byte_ranges: ranges
+            .into_iter()
+            .map(|range| range.start + start..range.end + start)
+            .collect(),
+        line_ranges: None,
+    }
+}
+
+/// Bounds on where a tree may differ from a baseline tree, maintained cheaply
+/// as edits are applied so that [`diff_piece_tree_windows`] can skip the
+/// untouched parts.
+///
+/// Edits only ever add pieces or cut existing ones, so content outside the
+/// region keeps its pieces: the bytes before `start` are shared by both trees,
+/// and so are the bytes after `end` in the current tree, which sit `growth`
+/// bytes later than in the baseline.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DirtyRegion {
+    /// No edits since the baseline
+    #[default]
+    Clean,
+    /// Edits since the baseline all fall inside `start..end` of the current tree
+    Bounded {
+        start: usize,
+        end: usize,
+        growth: isize,
+    },
+    /// The tree was rebuilt or restored wholesale, so nothing can be assumed
+    Unbounded,
+}
+
+impl DirtyRegion {
+    /// Record `len` bytes inserted at `offset` of the current tree
+    pub fn insert(&mut self, offset: usize, len: usize) {
+        *self = match *self {
+            DirtyRegion::Clean => DirtyRegion::Bounded {
+                start: offset,
+                end: offset + len,
+                growth: len as isize,
+            },
+            DirtyRegion::Bounded { start, end, growth } => DirtyRegion::Bounded {
+                start: start.min(offset),
+                end: if offset <= end {
+                    end + len
+                } else {
+                    offset + len
+                },
+                growth: growth + len as isize,
+            },
+            DirtyRegion::Unbounded => DirtyRegion::Unbounded,
+        };
+    }
+
+    /// Record `range` of the current tree being deleted
+    pub fn delete(&mut self, range: Range<usize>) {
+        let len = range.end.saturating_sub(range.start);
+        *self = match *self {
+            DirtyRegion::Clean => DirtyRegion::Bounded {
+                start: range.start,
+                end: range.start,
+                growth: -(len as isize),
+            },
+            DirtyRegion::Bounded { start, end, growth } => DirtyRegion::Bounded {
+                start: start.min(range.start),
+                end: if range.end <= end {
+                    end - len
+                } else {
+                    range.start
+                },
+                growth: growth - len as isize,
+            },
+            DirtyRegion::Unbounded => DirtyRegion::Unbounded,
+        };
+    }
+
+    /// The windows of the baseline and of the current tree that may differ,
+    /// or `None` when the whole trees have to be compared
+    pub fn windows(&self) -> Option<(Range<usize>, Range<usize>)> {
+        match *self {
+            DirtyRegion::Clean => Some((0..0, 0..0)),
+            DirtyRegion::Bounded { start, end, growth } => {
+                let before_end = end as isize - growth;
+                (before_end >= start as isize).then(|| (start..before_end as usize, start..end))
+            }
+            DirtyRegion::Unbounded => None,
+        }
+    }
+}
+
+fn equal_diff() -> PieceTreeDiff {
+    PieceTreeDiff {
+        equal: true,
+        byte_ranges: vec![0..0],
+        line_ranges: Some(vec![0..0]),
+    }
+}
+
+/// Diff two leaf sequences, returning the "after" spans and the changed byte
+/// ranges, or `None` when they hold the same pieces.
+fn diff_leaves(
+    before_leaves: Vec<LeafData>,
+    after_leaves: Vec<LeafData>,
+) -> Option<(Vec<Span>, Vec<Range<usize>>)> {
+    let before_leaves = normalize_leaves(before_leaves);
     let after_leaves = normalize_leaves(after_leaves);
 
     // Fast-path: identical leaf sequences.
     if leaf_slices_equal(&before_leaves, &after_leaves) {
-        return PieceTreeDiff {
-            equal: true,
-            byte_ranges: vec![0..0],
-            line_ranges: Some(vec![0..0]),
-        };
+        return None;
     }
 
     let before_spans = with_doc_offsets(&before_leaves);
     let after_spans = with_doc_offsets(&after_leaves);
 
-    let _total_after = sum_bytes(&after_leaves);
-
     // Longest common prefix at byte granularity.
     let prefix = common_prefix_bytes(&before_spans, &after_spans);
     // Longest common suffix without overlapping prefix.
     let suffix = common_suffix_bytes(&before_spans, &after_spans, prefix);
 
     let ranges = collect_diff_ranges(&before_spans, &after_spans, prefix, suffix);
-
-    // Map byte ranges to line ranges (best effort).
-    let line_ranges = line_ranges(&after_spans, &ranges, line_counter);
-
-    PieceTreeDiff {
-        equal: false,
-        byte_ranges: ranges,
-        line_ranges,
-    }
+    Some((after_spans, ranges))
 }
 
 fn collect_leaves(node: &Arc<PieceTreeNode>, out: &mut Vec<LeafData>) {
@@ -81,6 +215,55 @@ fn collect_leaves(node: &Arc<PieceTreeNode>, out: &mut Vec<LeafData>) {
     }
 }
 
+/// Collect the leaves overlapping `window`, clipped to it. `node_offset` is the
+/// document offset where `node` starts.
+fn collect_leaves_in(
+    node: &Arc<PieceTreeNode>,
+    node_offset: usize,
+    window: &Range<usize>,
+    out: &mut Vec<LeafData>,
+) {
+    match node.as_ref() {
+        PieceTreeNode::Internal {
+            left_bytes,
+            left,
+            right,
+            ..
+        } => {
+            let right_offset = node_offset + left_bytes;
+            if window.start < right_offset {
+                collect_leaves_in(left, node_offset, window, out);
+            }
+            if window.end > right_offset {
+                collect_leaves_in(right, right_offset, window, out);
+            }
+        }
+        PieceTreeNode::Leaf {
+            location,
+            offset,
+            bytes,
+            line_feed_cnt,
+        } => {
+            let start = window.start.max(node_offset);
+            let end = window.end.min(node_offset + bytes);
+            if start >= end {
+                return;
+            }
+            let line_feed_cnt = if end - start == *bytes {
+                *line_feed_cnt
+            } else {
+                None
+            };
+            out.push(LeafData::new(
+                *location,
+                offset + (start - node_offset),
+                end - start,
+                line_feed_cnt,
+            ));
+        }
+    }
+}
+
 fn leaves_equal(a: &LeafData, b: &LeafData) -> bool {
     a.location == b.location && a.offset == b.offset && a.bytes == b.bytes
 }
@@ -117,10 +300,6 @@ fn normalize_leaves(mut leaves: Vec<LeafData>) -> Vec<LeafData> {
     normalized
 }
 
-fn sum_bytes(leaves: &[LeafData]) -> usize {
-    leaves.iter().map(|leaf| leaf.bytes).sum()
-}
-
 #[derive(Clone)]
 struct Span {
     leaf: LeafData,
@@ -413,6 +592,10 @@ mod tests {
     use super::*;
     use crate::model::piece_tree::BufferLocation;
 
+    fn sum_bytes(leaves: &[LeafData]) -> usize {
+        leaves.iter().map(|leaf| leaf.bytes).sum()
+    }
+
     fn leaf(loc: BufferLocation, offset: usize, bytes: usize, lfs: Option<usize>) -> LeafData {
         LeafData::new(loc, offset, bytes, lfs)
     }
@@ -528,4 +711,58 @@ mod tests {
         // Only the inserted span should be marked.
         assert_eq!(diff.byte_ranges, vec![50..60]);
     }
+
+    #[test]
+    fn windowed_diff_matches_full_diff() {
+        let before = build(&[
+            leaf(BufferLocation::Stored(0), 0, 40, Some(2)),
+            leaf(BufferLocation::Stored(0), 40, 60, Some(3)),
+        ]);
+        // Insert 10 bytes at 50, then delete 70..75 of the result
+        let after = build(&[
+            leaf(BufferLocation::Stored(0), 0, 40, Some(2)),
+            leaf(BufferLocation::Stored(0), 40, 10, None),
+            leaf(BufferLocation::Added(1), 0, 10, Some(0)),
+            leaf(BufferLocation::Stored(0), 50, 10, None),
+            leaf(BufferLocation::Stored(0), 65, 35, None),
+        ]);
+
+        let mut region = DirtyRegion::default();
+        region.insert(50, 10);
+        region.delete(70..75);
+        assert_eq!(
+            region,
+            DirtyRegion::Bounded {
+                start: 50,
+                end: 70,
+                growth: 5
+            }
+        );
+
+        let (before_window, after_window) = region.windows().unwrap();
+        let windowed = diff_piece_tree_windows(&before, before_window, &after, after_window);
+        let full = diff_piece_trees(&before, &after, &count_line_feeds);
+        assert!(!windowed.equal);
+        assert_eq!(windowed.byte_ranges, full.byte_ranges);
+        assert_eq!(windowed.byte_ranges, vec![50..60]);
+    }
+
+    #[test]
+    fn windowed_diff_sees_undone_edits_as_equal() {
+        let leaves = [leaf(BufferLocation::Stored(0), 0, 100, Some(4))];
+        let before = build(&leaves);
+        let after = build(&[
+            leaf(BufferLocation::Stored(0), 0, 30, None),
+            leaf(BufferLocation::Stored(0), 30, 70, None),
+        ]);
+
+        let mut region = DirtyRegion::default();
+        region.insert(30, 5);
+        region.delete(30..35);
+
+        let (before_window, after_window) = region.windows().unwrap();
+        let diff = diff_piece_tree_windows(&before, before_window, &after, after_window);
+        assert!(diff.equal);
+        assert_eq!(diff.byte_ranges, vec![0..0]);
+    }
 }
@@ -0,0 +1,185 @@
+//! Units of the character offsets in LSP positions
+//!
+//! An LSP position is a line and a character offset into it, counted in the
+//! units the client and server agreed on at initialization: UTF-16 code
+//! units unless the server picked another encoding the client offered.
+//! Buffers hold UTF-8, so with UTF-8 an offset is the byte column as it is
+//! and needs no decoding of the line.
+
+/// Units of the character offset of an LSP position
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum PositionEncoding {
+    /// Bytes of UTF-8
+    Utf8,
+    /// UTF-16 code units, the protocol's default: two for a character
+    /// outside the Basic Multilingual Plane, such as most emoji
+    #[default]
+    Utf16,
+    /// Characters (Unicode code points)
+    Utf32,
+}
+
+impl PositionEncoding {
+    /// Parse an encoding as named by the protocol: `utf-8`, `utf-16` or
+    /// `utf-32`
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "utf-8" => Some(Self::Utf8),
+            "utf-16" => Some(Self::Utf16),
+            "utf-32" => Some(Self::Utf32),
+            _ => None,
+        }
+    }
+
+    /// The protocol's name of the encoding
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Utf8 => "utf-8",
+            Self::Utf16 => "utf-16",
+            Self::Utf32 => "utf-32",
+        }
+    }
+
+    /// Units of `c`
+    pub fn char_len(self, c: char) -> usize {
+        match self {
+            Self::Utf8 => c.len_utf8(),
+            Self::Utf16 => c.len_utf16(),
+            Self::Utf32 => 1,
+        }
+    }
+
+    /// Units of `text`, the offset of its end
+    pub fn units(self, text: &str) -> usize {
+        match self {
+            Self::Utf8 => text.len(),
+            Self::Utf16 => text.encode_utf16().count(),
+            Self::Utf32 => text.chars().count(),
+        }
+    }
+
+    /// Byte offset into `line` of the offset `units`
+    ///
+    /// An offset inside a character counts as after it, and one past the end
+    /// of `line` as its end.
+    pub fn byte_offset(self, line: &str, units: usize) -> usize {
+        if self == Self::Utf8 {
+            let mut offset = units.min(line.len());
+            while !line.is_char_boundary(offset) {
+                offset += 1;
+            }
+            return offset;
+        }
+        let mut counted = 0;
+        for (offset, c) in line.char_indices() {
+            if counted >= units {
+                return offset;
+            }
+            counted += self.char_len(c);
+        }
+        line.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALL: [PositionEncoding; 3] = [
+        PositionEncoding::Utf8,
+        PositionEncoding::Utf16,
+        PositionEncoding::Utf32,
+    ];
+
+    /// ASCII, two- and three-byte characters, and emoji outside the Basic
+    /// Multilingual Plane that UTF-16 encodes as surrogate pairs
+    const LINE: &str = "a é 中 😀 👍🏽x";
+
+    #[test]
+    fn test_names_round_trip() {
+        for encoding in ALL {
+            assert_eq!(PositionEncoding::from_name(encoding.name()), Some(encoding));
+        }
+        assert_eq!(PositionEncoding::from_name("UTF-16"), None);
+        assert_eq!(PositionEncoding::from_name("utf-7"), None);
+        assert_eq!(PositionEncoding::default(), PositionEncoding::Utf16);
+    }
+
+    #[test]
+    fn test_units_of_multi_byte_text() {
+        assert_eq!(PositionEncoding::Utf8.units("é"), 2);
+        assert_eq!(PositionEncoding::Utf16.units("é"), 1);
+        assert_eq!(PositionEncoding::Utf32.units("é"), 1);
+
+        assert_eq!(PositionEncoding::Utf8.units("中"), 3);
+        assert_eq!(PositionEncoding::Utf16.units("中"), 1);
+        assert_eq!(PositionEncoding::Utf32.units("中"), 1);
+
+        // A surrogate pair in UTF-16
+        assert_eq!(PositionEncoding::Utf8.units("😀"), 4);
+        assert_eq!(PositionEncoding::Utf16.units("😀"), 2);
+        assert_eq!(PositionEncoding::Utf32.units("😀"), 1);
+
+        // An emoji with a skin tone modifier is two code points
+        assert_eq!(PositionEncoding::Utf8.units("👍🏽"), 8);
+        assert_eq!(PositionEncoding::Utf16.units("👍🏽"), 4);
+        assert_eq!(PositionEncoding::Utf32.units("👍🏽"), 2);
+
+        assert_eq!(PositionEncoding::Utf8.units(LINE), LINE.len());
+        assert_eq!(PositionEncoding::Utf16.units(LINE), 14);
+        assert_eq!(PositionEncoding::Utf32.units(LINE), 11);
+    }
+
+    #[test]
+    fn test_offsets_round_trip_at_every_character() {
+        for encoding in ALL {
+            let boundaries = LINE
+                .char_indices()
+                .map(|(offset, _)| offset)
+                .chain([LINE.len()]);
+            for offset in boundaries {
+                let units = encoding.units(&LINE[..offset]);
+                assert_eq!(
+                    encoding.byte_offset(LINE, units),
+                    offset,
+                    "{:?} offset {} ({} units)",
+                    encoding,
+                    offset,
+                    units
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_offset_inside_a_character_counts_as_after_it() {
+        let line = "a😀b";
+        // Between the two halves of the surrogate pair
+        assert_eq!(PositionEncoding::Utf16.byte_offset(line, 2), 5);
+        // Inside the UTF-8 bytes of the emoji
+        for units in 2..=4 {
+            assert_eq!(PositionEncoding::Utf8.byte_offset(line, units), 5);
+        }
+        assert_eq!(PositionEncoding::Utf8.byte_offset("中", 1), 3);
+    }
+
+    #[test]
+    fn test_offset_past_the_end_is_the_end() {
+        for encoding in ALL {
+            assert_eq!(encoding.byte_offset(LINE, 100), LINE.len());
+            assert_eq!(encoding.byte_offset("", 3), 0);
+            assert_eq!(encoding.byte_offset("", 0), 0);
+        }
+    }
+
+    #[test]
+    fn test_encodings_differ_after_the_first_multi_byte_character() {
+        // The offset of "x", the last character of LINE
+        let x = LINE.len() - 1;
+        assert_eq!(PositionEncoding::Utf8.byte_offset(LINE, x), x);
+        assert_eq!(PositionEncoding::Utf16.byte_offset(LINE, 13), x);
+        assert_eq!(PositionEncoding::Utf32.byte_offset(LINE, 10), x);
+        // The same count in the wrong units lands elsewhere
+        assert_ne!(PositionEncoding::Utf16.byte_offset(LINE, x), x);
+    }
+}
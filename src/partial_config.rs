@@ -4,11 +4,16 @@
 //! enabling a 4-level overlay architecture (System → User → Project → Session).
 
 use crate::config::{
-    CursorStyle, FileBrowserConfig, FileExplorerConfig, FormatterConfig, HighlighterPreference,
-    Keybinding, KeybindingMapName, KeymapConfig, LanguageConfig, LineEndingOption, OnSaveAction,
-    TerminalConfig, ThemeName, WarningsConfig,
+    AmbiguousWidth, AutoRevertMode, ClipboardConfig, CursorShapeConfig, CursorStyle,
+    DataPathBreadcrumb, DataPathStyle, DistractionFreeConfig, FileBrowserConfig,
+    FileExplorerConfig, FilesConfig, FormatterConfig, HighlighterPreference, IndentRulesConfig,
+    InlineBlamePosition, Keybinding, KeybindingMapName, KeymapConfig, LanguageConfig,
+    LineEndingOption, LogConfig, MissingFileMode, MouseConfig, MultiCursorModifier, OnSaveAction,
+    PerformanceConfig, SaveActionFailure, SearchConfig, SessionSnapshotConfig, StatsConfig,
+    TerminalConfig, ThemeName, TodoKeyword, TodosConfig, UiConfig, UpdatesConfig, WarningsConfig,
+    WhitespaceRendering, WinbarVisibility, WordCompletionScope,
 };
-use crate::types::LspServerConfig;
+use crate::types::LspLanguageConfig;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
@@ -47,6 +52,23 @@ fn merge_hashmap<K: Clone + Eq + std::hash::Hash, V: Clone>(
     }
 }
 
+/// Merge two lists that add up: entries of the lower-precedence `other` come
+/// first and self's follow, so later entries (such as gitignore negations)
+/// win. An entry in both is kept at its place in self.
+fn merge_list_unique<T: Clone + PartialEq>(target: &mut Option<Vec<T>>, other: &Option<Vec<T>>) {
+    let Some(other) = other else {
+        return;
+    };
+    let own = target.take().unwrap_or_default();
+    let mut merged: Vec<T> = other
+        .iter()
+        .filter(|item| !own.contains(item))
+        .cloned()
+        .collect();
+    merged.extend(own);
+    *target = Some(merged);
+}
+
 /// Merge two HashMaps where values implement Merge (for recursive merging).
 fn merge_hashmap_recursive<K, V>(target: &mut Option<HashMap<K, V>>, other: &Option<HashMap<K, V>>)
 where
@@ -81,12 +103,24 @@ pub struct PartialConfig {
     pub file_explorer: Option<PartialFileExplorerConfig>,
     pub file_browser: Option<PartialFileBrowserConfig>,
     pub terminal: Option<PartialTerminalConfig>,
+    pub mouse: Option<PartialMouseConfig>,
     pub keybindings: Option<Vec<Keybinding>>,
     pub keybinding_maps: Option<HashMap<String, KeymapConfig>>,
     pub active_keybinding_map: Option<KeybindingMapName>,
     pub languages: Option<HashMap<String, PartialLanguageConfig>>,
-    pub lsp: Option<HashMap<String, LspServerConfig>>,
+    pub lsp: Option<HashMap<String, LspLanguageConfig>>,
     pub warnings: Option<PartialWarningsConfig>,
+    pub todos: Option<PartialTodosConfig>,
+    pub clipboard: Option<PartialClipboardConfig>,
+    pub files: Option<PartialFilesConfig>,
+    pub distraction_free: Option<PartialDistractionFreeConfig>,
+    pub search: Option<PartialSearchConfig>,
+    pub session: Option<PartialSessionSnapshotConfig>,
+    pub updates: Option<PartialUpdatesConfig>,
+    pub stats: Option<PartialStatsConfig>,
+    pub log: Option<PartialLogConfig>,
+    pub performance: Option<PartialPerformanceConfig>,
+    pub ui: Option<PartialUiConfig>,
 }
 
 impl Merge for PartialConfig {
@@ -101,7 +135,19 @@ impl Merge for PartialConfig {
         merge_partial(&mut self.file_explorer, &other.file_explorer);
         merge_partial(&mut self.file_browser, &other.file_browser);
         merge_partial(&mut self.terminal, &other.terminal);
+        merge_partial(&mut self.mouse, &other.mouse);
         merge_partial(&mut self.warnings, &other.warnings);
+        merge_partial(&mut self.todos, &other.todos);
+        merge_partial(&mut self.clipboard, &other.clipboard);
+        merge_partial(&mut self.files, &other.files);
+        merge_partial(&mut self.distraction_free, &other.distraction_free);
+        merge_partial(&mut self.search, &other.search);
+        merge_partial(&mut self.session, &other.session);
+        merge_partial(&mut self.updates, &other.updates);
+        merge_partial(&mut self.stats, &other.stats);
+        merge_partial(&mut self.log, &other.log);
+        merge_partial(&mut self.performance, &other.performance);
+        merge_partial(&mut self.ui, &other.ui);
 
         // Lists: higher precedence replaces (per design doc)
         self.keybindings.merge_from(&other.keybindings);
@@ -133,6 +179,8 @@ pub struct PartialEditorConfig {
     pub auto_indent: Option<bool>,
     pub line_numbers: Option<bool>,
     pub relative_line_numbers: Option<bool>,
+    pub gutter_columns: Option<Vec<String>>,
+    pub gutter_column_widths: Option<HashMap<String, usize>>,
     pub scroll_offset: Option<usize>,
     pub syntax_highlighting: Option<bool>,
     pub line_wrap: Option<bool>,
@@ -149,8 +197,21 @@ pub struct PartialEditorConfig {
     pub double_click_time_ms: Option<u64>,
     pub auto_revert_poll_interval_ms: Option<u64>,
     pub file_tree_poll_interval_ms: Option<u64>,
+    pub plugin_buffer_idle_close_secs: Option<u64>,
     pub default_line_ending: Option<LineEndingOption>,
     pub cursor_style: Option<CursorStyle>,
+    pub ambiguous_width: Option<AmbiguousWidth>,
+    pub inline_blame_position: Option<InlineBlamePosition>,
+    pub word_completion: Option<WordCompletionScope>,
+    pub sentence_abbreviations: Option<Vec<String>>,
+    pub auto_complete: Option<bool>,
+    pub auto_complete_delay_ms: Option<u64>,
+    pub undo_skips_plugin_edits: Option<bool>,
+    pub data_path_breadcrumb: Option<DataPathBreadcrumb>,
+    pub data_path_style: Option<DataPathStyle>,
+    pub plugin_text_limit_bytes: Option<u64>,
+    pub plugin_hook_chain_depth: Option<u32>,
+    pub plugin_hook_repeat_limit: Option<u32>,
 }
 
 impl Merge for PartialEditorConfig {
@@ -160,6 +221,8 @@ impl Merge for PartialEditorConfig {
         self.line_numbers.merge_from(&other.line_numbers);
         self.relative_line_numbers
             .merge_from(&other.relative_line_numbers);
+        self.gutter_columns.merge_from(&other.gutter_columns);
+        merge_hashmap(&mut self.gutter_column_widths, &other.gutter_column_widths);
         self.scroll_offset.merge_from(&other.scroll_offset);
         self.syntax_highlighting
             .merge_from(&other.syntax_highlighting);
@@ -188,9 +251,31 @@ impl Merge for PartialEditorConfig {
             .merge_from(&other.auto_revert_poll_interval_ms);
         self.file_tree_poll_interval_ms
             .merge_from(&other.file_tree_poll_interval_ms);
+        self.plugin_buffer_idle_close_secs
+            .merge_from(&other.plugin_buffer_idle_close_secs);
         self.default_line_ending
             .merge_from(&other.default_line_ending);
         self.cursor_style.merge_from(&other.cursor_style);
+        self.ambiguous_width.merge_from(&other.ambiguous_width);
+        self.inline_blame_position
+            .merge_from(&other.inline_blame_position);
+        self.word_completion.merge_from(&other.word_completion);
+        self.sentence_abbreviations
+            .merge_from(&other.sentence_abbreviations);
+        self.auto_complete.merge_from(&other.auto_complete);
+        self.auto_complete_delay_ms
+            .merge_from(&other.auto_complete_delay_ms);
+        self.undo_skips_plugin_edits
+            .merge_from(&other.undo_skips_plugin_edits);
+        self.data_path_breadcrumb
+            .merge_from(&other.data_path_breadcrumb);
+        self.data_path_style.merge_from(&other.data_path_style);
+        self.plugin_text_limit_bytes
+            .merge_from(&other.plugin_text_limit_bytes);
+        self.plugin_hook_chain_depth
+            .merge_from(&other.plugin_hook_chain_depth);
+        self.plugin_hook_repeat_limit
+            .merge_from(&other.plugin_hook_repeat_limit);
     }
 }
 
@@ -203,6 +288,10 @@ pub struct PartialFileExplorerConfig {
     pub show_gitignored: Option<bool>,
     pub custom_ignore_patterns: Option<Vec<String>>,
     pub width: Option<f32>,
+    pub max_visible_entries: Option<usize>,
+    pub show_entry_counts: Option<bool>,
+    pub show_details: Option<bool>,
+    pub refresh_debounce_ms: Option<u64>,
 }
 
 impl Merge for PartialFileExplorerConfig {
@@ -213,6 +302,12 @@ impl Merge for PartialFileExplorerConfig {
         self.custom_ignore_patterns
             .merge_from(&other.custom_ignore_patterns);
         self.width.merge_from(&other.width);
+        self.max_visible_entries
+            .merge_from(&other.max_visible_entries);
+        self.show_entry_counts.merge_from(&other.show_entry_counts);
+        self.show_details.merge_from(&other.show_details);
+        self.refresh_debounce_ms
+            .merge_from(&other.refresh_debounce_ms);
     }
 }
 
@@ -243,6 +338,25 @@ impl Merge for PartialTerminalConfig {
     }
 }
 
+/// Partial mouse configuration.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct PartialMouseConfig {
+    pub scroll_lines: Option<usize>,
+    pub horizontal_scroll_columns: Option<usize>,
+    pub multi_cursor_modifier: Option<MultiCursorModifier>,
+}
+
+impl Merge for PartialMouseConfig {
+    fn merge_from(&mut self, other: &Self) {
+        self.scroll_lines.merge_from(&other.scroll_lines);
+        self.horizontal_scroll_columns
+            .merge_from(&other.horizontal_scroll_columns);
+        self.multi_cursor_modifier
+            .merge_from(&other.multi_cursor_modifier);
+    }
+}
+
 /// Partial warnings configuration.
 #[derive(Debug, Clone, Default, Deserialize, Serialize)]
 #[serde(default)]
@@ -257,6 +371,246 @@ impl Merge for PartialWarningsConfig {
     }
 }
 
+/// Partial TODO comment configuration.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct PartialTodosConfig {
+    pub highlight: Option<bool>,
+    pub keywords: Option<Vec<TodoKeyword>>,
+}
+
+impl Merge for PartialTodosConfig {
+    fn merge_from(&mut self, other: &Self) {
+        self.highlight.merge_from(&other.highlight);
+        self.keywords.merge_from(&other.keywords);
+    }
+}
+
+/// Partial clipboard history configuration.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct PartialClipboardConfig {
+    pub history_size: Option<usize>,
+    pub history_max_bytes: Option<usize>,
+    pub record_external_pastes: Option<bool>,
+    pub save_history_in_session: Option<bool>,
+    pub reindent_pastes: Option<bool>,
+    pub system_timeout_ms: Option<u64>,
+}
+
+impl Merge for PartialClipboardConfig {
+    fn merge_from(&mut self, other: &Self) {
+        self.history_size.merge_from(&other.history_size);
+        self.history_max_bytes.merge_from(&other.history_max_bytes);
+        self.record_external_pastes
+            .merge_from(&other.record_external_pastes);
+        self.save_history_in_session
+            .merge_from(&other.save_history_in_session);
+        self.reindent_pastes.merge_from(&other.reindent_pastes);
+        self.system_timeout_ms.merge_from(&other.system_timeout_ms);
+    }
+}
+
+/// Partial configuration for files changed on disk.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct PartialFilesConfig {
+    pub auto_revert: Option<AutoRevertMode>,
+    pub auto_revert_exclude: Option<Vec<String>>,
+    pub sudo_save_command: Option<Vec<String>>,
+    pub ssh_args: Option<Vec<String>>,
+    pub ssh_control_master: Option<bool>,
+    pub missing_files: Option<MissingFileMode>,
+    pub protected_globs: Option<Vec<String>>,
+}
+
+impl Merge for PartialFilesConfig {
+    fn merge_from(&mut self, other: &Self) {
+        self.auto_revert.merge_from(&other.auto_revert);
+        self.auto_revert_exclude
+            .merge_from(&other.auto_revert_exclude);
+        self.sudo_save_command.merge_from(&other.sudo_save_command);
+        self.ssh_args.merge_from(&other.ssh_args);
+        self.ssh_control_master
+            .merge_from(&other.ssh_control_master);
+        self.missing_files.merge_from(&other.missing_files);
+        // Added up rather than replaced, so a project adds to the user's
+        merge_list_unique(&mut self.protected_globs, &other.protected_globs);
+    }
+}
+
+/// Partial distraction-free mode configuration.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct PartialDistractionFreeConfig {
+    pub width: Option<u16>,
+    pub dim_other_paragraphs: Option<bool>,
+    pub restore_with_session: Option<bool>,
+}
+
+impl Merge for PartialDistractionFreeConfig {
+    fn merge_from(&mut self, other: &Self) {
+        self.width.merge_from(&other.width);
+        self.dim_other_paragraphs
+            .merge_from(&other.dim_other_paragraphs);
+        self.restore_with_session
+            .merge_from(&other.restore_with_session);
+    }
+}
+
+/// Partial case matching configuration.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct PartialSearchConfig {
+    pub smart_case: Option<bool>,
+    pub project_smart_case: Option<bool>,
+    pub completion_smart_case: Option<bool>,
+    pub preserve_case: Option<bool>,
+}
+
+impl Merge for PartialSearchConfig {
+    fn merge_from(&mut self, other: &Self) {
+        self.smart_case.merge_from(&other.smart_case);
+        self.project_smart_case
+            .merge_from(&other.project_smart_case);
+        self.completion_smart_case
+            .merge_from(&other.completion_smart_case);
+        self.preserve_case.merge_from(&other.preserve_case);
+    }
+}
+
+/// Partial session snapshot configuration.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct PartialSessionSnapshotConfig {
+    pub snapshots: Option<usize>,
+    pub snapshots_max_bytes: Option<usize>,
+}
+
+impl Merge for PartialSessionSnapshotConfig {
+    fn merge_from(&mut self, other: &Self) {
+        self.snapshots.merge_from(&other.snapshots);
+        self.snapshots_max_bytes
+            .merge_from(&other.snapshots_max_bytes);
+    }
+}
+
+/// Partial self-update configuration.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct PartialUpdatesConfig {
+    pub self_update: Option<bool>,
+}
+
+impl Merge for PartialUpdatesConfig {
+    fn merge_from(&mut self, other: &Self) {
+        self.self_update.merge_from(&other.self_update);
+    }
+}
+
+/// Partial usage statistics configuration.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct PartialStatsConfig {
+    pub enabled: Option<bool>,
+    pub retention_days: Option<u32>,
+}
+
+impl Merge for PartialStatsConfig {
+    fn merge_from(&mut self, other: &Self) {
+        self.enabled.merge_from(&other.enabled);
+        self.retention_days.merge_from(&other.retention_days);
+    }
+}
+
+/// Partial editor log configuration.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct PartialLogConfig {
+    pub max_size_mb: Option<u64>,
+    pub max_files: Option<usize>,
+}
+
+impl Merge for PartialLogConfig {
+    fn merge_from(&mut self, other: &Self) {
+        self.max_size_mb.merge_from(&other.max_size_mb);
+        self.max_files.merge_from(&other.max_files);
+    }
+}
+
+/// Partial cache and buffer memory configuration.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct PartialPerformanceConfig {
+    pub cache_budget_mb: Option<u64>,
+    pub auto_compact: Option<bool>,
+    pub auto_compact_idle_minutes: Option<u64>,
+    pub auto_compact_undo_depth: Option<usize>,
+}
+
+impl Merge for PartialPerformanceConfig {
+    fn merge_from(&mut self, other: &Self) {
+        self.cache_budget_mb.merge_from(&other.cache_budget_mb);
+        self.auto_compact.merge_from(&other.auto_compact);
+        self.auto_compact_idle_minutes
+            .merge_from(&other.auto_compact_idle_minutes);
+        self.auto_compact_undo_depth
+            .merge_from(&other.auto_compact_undo_depth);
+    }
+}
+
+/// Partial user interface configuration.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct PartialUiConfig {
+    pub cursor: Option<PartialCursorShapeConfig>,
+    pub render_whitespace: Option<WhitespaceRendering>,
+    pub transparent_background: Option<bool>,
+    pub rulers: Option<Vec<u16>>,
+    pub highlight_overlength: Option<bool>,
+    pub ascii_only: Option<bool>,
+    pub winbar: Option<WinbarVisibility>,
+    pub minimum_contrast: Option<f64>,
+    pub light_theme: Option<ThemeName>,
+    pub dark_theme: Option<ThemeName>,
+}
+
+impl Merge for PartialUiConfig {
+    fn merge_from(&mut self, other: &Self) {
+        merge_partial(&mut self.cursor, &other.cursor);
+        self.render_whitespace.merge_from(&other.render_whitespace);
+        self.transparent_background
+            .merge_from(&other.transparent_background);
+        self.rulers.merge_from(&other.rulers);
+        self.highlight_overlength
+            .merge_from(&other.highlight_overlength);
+        self.ascii_only.merge_from(&other.ascii_only);
+        self.winbar.merge_from(&other.winbar);
+        self.minimum_contrast.merge_from(&other.minimum_contrast);
+        self.light_theme.merge_from(&other.light_theme);
+        self.dark_theme.merge_from(&other.dark_theme);
+    }
+}
+
+/// Partial cursor shape configuration.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct PartialCursorShapeConfig {
+    pub normal: Option<CursorStyle>,
+    pub insert: Option<CursorStyle>,
+    pub selection: Option<CursorStyle>,
+    pub modes: Option<HashMap<String, CursorStyle>>,
+}
+
+impl Merge for PartialCursorShapeConfig {
+    fn merge_from(&mut self, other: &Self) {
+        self.normal.merge_from(&other.normal);
+        self.insert.merge_from(&other.insert);
+        self.selection.merge_from(&other.selection);
+        merge_hashmap(&mut self.modes, &other.modes);
+    }
+}
+
 /// Partial language configuration.
 #[derive(Debug, Clone, Default, Deserialize, Serialize)]
 #[serde(default)]
@@ -274,6 +628,14 @@ pub struct PartialLanguageConfig {
     pub formatter: Option<FormatterConfig>,
     pub format_on_save: Option<bool>,
     pub on_save: Option<Vec<OnSaveAction>>,
+    pub save_actions: Option<Vec<String>>,
+    pub save_actions_on_failure: Option<SaveActionFailure>,
+    pub save_actions_timeout_ms: Option<u64>,
+    pub indent_rules: Option<IndentRulesConfig>,
+    pub on_type_formatting: Option<bool>,
+    pub rulers: Option<Vec<u16>>,
+    pub auto_complete: Option<bool>,
+    pub completion_trigger_characters: Option<Vec<String>>,
 }
 
 impl Merge for PartialLanguageConfig {
@@ -292,6 +654,18 @@ impl Merge for PartialLanguageConfig {
         self.formatter.merge_from(&other.formatter);
         self.format_on_save.merge_from(&other.format_on_save);
         self.on_save.merge_from(&other.on_save);
+        self.save_actions.merge_from(&other.save_actions);
+        self.save_actions_on_failure
+            .merge_from(&other.save_actions_on_failure);
+        self.save_actions_timeout_ms
+            .merge_from(&other.save_actions_timeout_ms);
+        self.indent_rules.merge_from(&other.indent_rules);
+        self.on_type_formatting
+            .merge_from(&other.on_type_formatting);
+        self.rulers.merge_from(&other.rulers);
+        self.auto_complete.merge_from(&other.auto_complete);
+        self.completion_trigger_characters
+            .merge_from(&other.completion_trigger_characters);
     }
 }
 
@@ -304,6 +678,8 @@ impl From<&crate::config::EditorConfig> for PartialEditorConfig {
             auto_indent: Some(cfg.auto_indent),
             line_numbers: Some(cfg.line_numbers),
             relative_line_numbers: Some(cfg.relative_line_numbers),
+            gutter_columns: Some(cfg.gutter_columns.clone()),
+            gutter_column_widths: Some(cfg.gutter_column_widths.clone()),
             scroll_offset: Some(cfg.scroll_offset),
             syntax_highlighting: Some(cfg.syntax_highlighting),
             line_wrap: Some(cfg.line_wrap),
@@ -320,8 +696,21 @@ impl From<&crate::config::EditorConfig> for PartialEditorConfig {
             double_click_time_ms: Some(cfg.double_click_time_ms),
             auto_revert_poll_interval_ms: Some(cfg.auto_revert_poll_interval_ms),
             file_tree_poll_interval_ms: Some(cfg.file_tree_poll_interval_ms),
+            plugin_buffer_idle_close_secs: Some(cfg.plugin_buffer_idle_close_secs),
             default_line_ending: Some(cfg.default_line_ending.clone()),
             cursor_style: Some(cfg.cursor_style),
+            ambiguous_width: Some(cfg.ambiguous_width),
+            inline_blame_position: Some(cfg.inline_blame_position),
+            word_completion: Some(cfg.word_completion),
+            sentence_abbreviations: Some(cfg.sentence_abbreviations.clone()),
+            auto_complete: Some(cfg.auto_complete),
+            auto_complete_delay_ms: Some(cfg.auto_complete_delay_ms),
+            undo_skips_plugin_edits: Some(cfg.undo_skips_plugin_edits),
+            data_path_breadcrumb: Some(cfg.data_path_breadcrumb),
+            data_path_style: Some(cfg.data_path_style),
+            plugin_text_limit_bytes: Some(cfg.plugin_text_limit_bytes),
+            plugin_hook_chain_depth: Some(cfg.plugin_hook_chain_depth),
+            plugin_hook_repeat_limit: Some(cfg.plugin_hook_repeat_limit),
         }
     }
 }
@@ -336,6 +725,12 @@ impl PartialEditorConfig {
             relative_line_numbers: self
                 .relative_line_numbers
                 .unwrap_or(defaults.relative_line_numbers),
+            gutter_columns: self
+                .gutter_columns
+                .unwrap_or_else(|| defaults.gutter_columns.clone()),
+            gutter_column_widths: self
+                .gutter_column_widths
+                .unwrap_or_else(|| defaults.gutter_column_widths.clone()),
             scroll_offset: self.scroll_offset.unwrap_or(defaults.scroll_offset),
             syntax_highlighting: self
                 .syntax_highlighting
@@ -376,10 +771,41 @@ impl PartialEditorConfig {
             file_tree_poll_interval_ms: self
                 .file_tree_poll_interval_ms
                 .unwrap_or(defaults.file_tree_poll_interval_ms),
+            plugin_buffer_idle_close_secs: self
+                .plugin_buffer_idle_close_secs
+                .unwrap_or(defaults.plugin_buffer_idle_close_secs),
             default_line_ending: self
                 .default_line_ending
                 .unwrap_or(defaults.default_line_ending.clone()),
             cursor_style: self.cursor_style.unwrap_or(defaults.cursor_style),
+            ambiguous_width: self.ambiguous_width.unwrap_or(defaults.ambiguous_width),
+            inline_blame_position: self
+                .inline_blame_position
+                .unwrap_or(defaults.inline_blame_position),
+            word_completion: self.word_completion.unwrap_or(defaults.word_completion),
+            sentence_abbreviations: self
+                .sentence_abbreviations
+                .unwrap_or_else(|| defaults.sentence_abbreviations.clone()),
+            auto_complete: self.auto_complete.unwrap_or(defaults.auto_complete),
+            auto_complete_delay_ms: self
+                .auto_complete_delay_ms
+                .unwrap_or(defaults.auto_complete_delay_ms),
+            undo_skips_plugin_edits: self
+                .undo_skips_plugin_edits
+                .unwrap_or(defaults.undo_skips_plugin_edits),
+            data_path_breadcrumb: self
+                .data_path_breadcrumb
+                .unwrap_or(defaults.data_path_breadcrumb),
+            data_path_style: self.data_path_style.unwrap_or(defaults.data_path_style),
+            plugin_text_limit_bytes: self
+                .plugin_text_limit_bytes
+                .unwrap_or(defaults.plugin_text_limit_bytes),
+            plugin_hook_chain_depth: self
+                .plugin_hook_chain_depth
+                .unwrap_or(defaults.plugin_hook_chain_depth),
+            plugin_hook_repeat_limit: self
+                .plugin_hook_repeat_limit
+                .unwrap_or(defaults.plugin_hook_repeat_limit),
         }
     }
 }
@@ -392,6 +818,10 @@ impl From<&FileExplorerConfig> for PartialFileExplorerConfig {
             show_gitignored: Some(cfg.show_gitignored),
             custom_ignore_patterns: Some(cfg.custom_ignore_patterns.clone()),
             width: Some(cfg.width),
+            max_visible_entries: Some(cfg.max_visible_entries),
+            show_entry_counts: Some(cfg.show_entry_counts),
+            show_details: Some(cfg.show_details),
+            refresh_debounce_ms: Some(cfg.refresh_debounce_ms),
         }
     }
 }
@@ -406,6 +836,14 @@ impl PartialFileExplorerConfig {
                 .custom_ignore_patterns
                 .unwrap_or_else(|| defaults.custom_ignore_patterns.clone()),
             width: self.width.unwrap_or(defaults.width),
+            max_visible_entries: self
+                .max_visible_entries
+                .unwrap_or(defaults.max_visible_entries),
+            show_entry_counts: self.show_entry_counts.unwrap_or(defaults.show_entry_counts),
+            show_details: self.show_details.unwrap_or(defaults.show_details),
+            refresh_debounce_ms: self
+                .refresh_debounce_ms
+                .unwrap_or(defaults.refresh_debounce_ms),
         }
     }
 }
@@ -444,6 +882,30 @@ impl PartialTerminalConfig {
     }
 }
 
+impl From<&MouseConfig> for PartialMouseConfig {
+    fn from(cfg: &MouseConfig) -> Self {
+        Self {
+            scroll_lines: Some(cfg.scroll_lines),
+            horizontal_scroll_columns: Some(cfg.horizontal_scroll_columns),
+            multi_cursor_modifier: Some(cfg.multi_cursor_modifier),
+        }
+    }
+}
+
+impl PartialMouseConfig {
+    pub fn resolve(self, defaults: &MouseConfig) -> MouseConfig {
+        MouseConfig {
+            scroll_lines: self.scroll_lines.unwrap_or(defaults.scroll_lines),
+            horizontal_scroll_columns: self
+                .horizontal_scroll_columns
+                .unwrap_or(defaults.horizontal_scroll_columns),
+            multi_cursor_modifier: self
+                .multi_cursor_modifier
+                .unwrap_or(defaults.multi_cursor_modifier),
+        }
+    }
+}
+
 impl From<&WarningsConfig> for PartialWarningsConfig {
     fn from(cfg: &WarningsConfig) -> Self {
         Self {
@@ -462,6 +924,309 @@ impl PartialWarningsConfig {
     }
 }
 
+impl From<&TodosConfig> for PartialTodosConfig {
+    fn from(cfg: &TodosConfig) -> Self {
+        Self {
+            highlight: Some(cfg.highlight),
+            keywords: Some(cfg.keywords.clone()),
+        }
+    }
+}
+
+impl PartialTodosConfig {
+    pub fn resolve(self, defaults: &TodosConfig) -> TodosConfig {
+        TodosConfig {
+            highlight: self.highlight.unwrap_or(defaults.highlight),
+            keywords: self.keywords.unwrap_or_else(|| defaults.keywords.clone()),
+        }
+    }
+}
+
+impl From<&ClipboardConfig> for PartialClipboardConfig {
+    fn from(cfg: &ClipboardConfig) -> Self {
+        Self {
+            history_size: Some(cfg.history_size),
+            history_max_bytes: Some(cfg.history_max_bytes),
+            record_external_pastes: Some(cfg.record_external_pastes),
+            save_history_in_session: Some(cfg.save_history_in_session),
+            reindent_pastes: Some(cfg.reindent_pastes),
+            system_timeout_ms: Some(cfg.system_timeout_ms),
+        }
+    }
+}
+
+impl From<&FilesConfig> for PartialFilesConfig {
+    fn from(cfg: &FilesConfig) -> Self {
+        Self {
+            auto_revert: Some(cfg.auto_revert),
+            auto_revert_exclude: Some(cfg.auto_revert_exclude.clone()),
+            sudo_save_command: Some(cfg.sudo_save_command.clone()),
+            ssh_args: Some(cfg.ssh_args.clone()),
+            ssh_control_master: Some(cfg.ssh_control_master),
+            missing_files: Some(cfg.missing_files),
+            protected_globs: Some(cfg.protected_globs.clone()),
+        }
+    }
+}
+
+impl PartialFilesConfig {
+    pub fn resolve(self, defaults: &FilesConfig) -> FilesConfig {
+        FilesConfig {
+            auto_revert: self.auto_revert.unwrap_or(defaults.auto_revert),
+            auto_revert_exclude: self
+                .auto_revert_exclude
+                .unwrap_or_else(|| defaults.auto_revert_exclude.clone()),
+            sudo_save_command: self
+                .sudo_save_command
+                .unwrap_or_else(|| defaults.sudo_save_command.clone()),
+            ssh_args: self.ssh_args.unwrap_or_else(|| defaults.ssh_args.clone()),
+            ssh_control_master: self
+                .ssh_control_master
+                .unwrap_or(defaults.ssh_control_master),
+            missing_files: self.missing_files.unwrap_or(defaults.missing_files),
+            protected_globs: {
+                let mut globs = self.protected_globs;
+                merge_list_unique(&mut globs, &Some(defaults.protected_globs.clone()));
+                globs.unwrap_or_default()
+            },
+        }
+    }
+}
+
+impl From<&DistractionFreeConfig> for PartialDistractionFreeConfig {
+    fn from(cfg: &DistractionFreeConfig) -> Self {
+        Self {
+            width: Some(cfg.width),
+            dim_other_paragraphs: Some(cfg.dim_other_paragraphs),
+            restore_with_session: Some(cfg.restore_with_session),
+        }
+    }
+}
+
+impl PartialDistractionFreeConfig {
+    pub fn resolve(self, defaults: &DistractionFreeConfig) -> DistractionFreeConfig {
+        DistractionFreeConfig {
+            width: self.width.unwrap_or(defaults.width),
+            dim_other_paragraphs: self
+                .dim_other_paragraphs
+                .unwrap_or(defaults.dim_other_paragraphs),
+            restore_with_session: self
+                .restore_with_session
+                .unwrap_or(defaults.restore_with_session),
+        }
+    }
+}
+
+impl From<&SearchConfig> for PartialSearchConfig {
+    fn from(cfg: &SearchConfig) -> Self {
+        Self {
+            smart_case: Some(cfg.smart_case),
+            project_smart_case: Some(cfg.project_smart_case),
+            completion_smart_case: Some(cfg.completion_smart_case),
+            preserve_case: Some(cfg.preserve_case),
+        }
+    }
+}
+
+impl PartialSearchConfig {
+    pub fn resolve(self, defaults: &SearchConfig) -> SearchConfig {
+        SearchConfig {
+            smart_case: self.smart_case.unwrap_or(defaults.smart_case),
+            project_smart_case: self
+                .project_smart_case
+                .unwrap_or(defaults.project_smart_case),
+            completion_smart_case: self
+                .completion_smart_case
+                .unwrap_or(defaults.completion_smart_case),
+            preserve_case: self.preserve_case.unwrap_or(defaults.preserve_case),
+        }
+    }
+}
+
+impl From<&SessionSnapshotConfig> for PartialSessionSnapshotConfig {
+    fn from(cfg: &SessionSnapshotConfig) -> Self {
+        Self {
+            snapshots: Some(cfg.snapshots),
+            snapshots_max_bytes: Some(cfg.snapshots_max_bytes),
+        }
+    }
+}
+
+impl PartialSessionSnapshotConfig {
+    pub fn resolve(self, defaults: &SessionSnapshotConfig) -> SessionSnapshotConfig {
+        SessionSnapshotConfig {
+            snapshots: self.snapshots.unwrap_or(defaults.snapshots),
+            snapshots_max_bytes: self
+                .snapshots_max_bytes
+                .unwrap_or(defaults.snapshots_max_bytes),
+        }
+    }
+}
+
+impl From<&UpdatesConfig> for PartialUpdatesConfig {
+    fn from(cfg: &UpdatesConfig) -> Self {
+        Self {
+            self_update: Some(cfg.self_update),
+        }
+    }
+}
+
+impl PartialUpdatesConfig {
+    pub fn resolve(self, defaults: &UpdatesConfig) -> UpdatesConfig {
+        UpdatesConfig {
+            self_update: self.self_update.unwrap_or(defaults.self_update),
+        }
+    }
+}
+
+impl From<&StatsConfig> for PartialStatsConfig {
+    fn from(cfg: &StatsConfig) -> Self {
+        Self {
+            enabled: Some(cfg.enabled),
+            retention_days: Some(cfg.retention_days),
+        }
+    }
+}
+
+impl PartialStatsConfig {
+    pub fn resolve(self, defaults: &StatsConfig) -> StatsConfig {
+        StatsConfig {
+            enabled: self.enabled.unwrap_or(defaults.enabled),
+            retention_days: self.retention_days.unwrap_or(defaults.retention_days),
+        }
+    }
+}
+
+impl From<&LogConfig> for PartialLogConfig {
+    fn from(cfg: &LogConfig) -> Self {
+        Self {
+            max_size_mb: Some(cfg.max_size_mb),
+            max_files: Some(cfg.max_files),
+        }
+    }
+}
+
+impl PartialLogConfig {
+    pub fn resolve(self, defaults: &LogConfig) -> LogConfig {
+        LogConfig {
+            max_size_mb: self.max_size_mb.unwrap_or(defaults.max_size_mb),
+            max_files: self.max_files.unwrap_or(defaults.max_files),
+        }
+    }
+}
+
+impl From<&PerformanceConfig> for PartialPerformanceConfig {
+    fn from(cfg: &PerformanceConfig) -> Self {
+        Self {
+            cache_budget_mb: Some(cfg.cache_budget_mb),
+            auto_compact: Some(cfg.auto_compact),
+            auto_compact_idle_minutes: Some(cfg.auto_compact_idle_minutes),
+            auto_compact_undo_depth: Some(cfg.auto_compact_undo_depth),
+        }
+    }
+}
+
+impl PartialPerformanceConfig {
+    pub fn resolve(self, defaults: &PerformanceConfig) -> PerformanceConfig {
+        PerformanceConfig {
+            cache_budget_mb: self.cache_budget_mb.unwrap_or(defaults.cache_budget_mb),
+            auto_compact: self.auto_compact.unwrap_or(defaults.auto_compact),
+            auto_compact_idle_minutes: self
+                .auto_compact_idle_minutes
+                .unwrap_or(defaults.auto_compact_idle_minutes),
+            auto_compact_undo_depth: self
+                .auto_compact_undo_depth
+                .unwrap_or(defaults.auto_compact_undo_depth),
+        }
+    }
+}
+
+impl From<&UiConfig> for PartialUiConfig {
+    fn from(cfg: &UiConfig) -> Self {
+        Self {
+            cursor: Some(PartialCursorShapeConfig::from(&cfg.cursor)),
+            render_whitespace: Some(cfg.render_whitespace),
+            transparent_background: Some(cfg.transparent_background),
+            rulers: Some(cfg.rulers.clone()),
+            highlight_overlength: Some(cfg.highlight_overlength),
+            ascii_only: cfg.ascii_only,
+            winbar: Some(cfg.winbar),
+            minimum_contrast: cfg.minimum_contrast,
+            light_theme: cfg.light_theme.clone(),
+            dark_theme: cfg.dark_theme.clone(),
+        }
+    }
+}
+
+impl PartialUiConfig {
+    pub fn resolve(self, defaults: &UiConfig) -> UiConfig {
+        UiConfig {
+            cursor: self
+                .cursor
+                .map(|c| c.resolve(&defaults.cursor))
+                .unwrap_or_else(|| defaults.cursor.clone()),
+            render_whitespace: self.render_whitespace.unwrap_or(defaults.render_whitespace),
+            transparent_background: self
+                .transparent_background
+                .unwrap_or(defaults.transparent_background),
+            rulers: self.rulers.unwrap_or_else(|| defaults.rulers.clone()),
+            highlight_overlength: self
+                .highlight_overlength
+                .unwrap_or(defaults.highlight_overlength),
+            ascii_only: self.ascii_only.or(defaults.ascii_only),
+            winbar: self.winbar.unwrap_or(defaults.winbar),
+            minimum_contrast: self.minimum_contrast.or(defaults.minimum_contrast),
+            light_theme: self.light_theme.or_else(|| defaults.light_theme.clone()),
+            dark_theme: self.dark_theme.or_else(|| defaults.dark_theme.clone()),
+        }
+    }
+}
+
+impl From<&CursorShapeConfig> for PartialCursorShapeConfig {
+    fn from(cfg: &CursorShapeConfig) -> Self {
+        Self {
+            normal: cfg.normal,
+            insert: cfg.insert,
+            selection: cfg.selection,
+            modes: Some(cfg.modes.clone()),
+        }
+    }
+}
+
+impl PartialCursorShapeConfig {
+    pub fn resolve(self, defaults: &CursorShapeConfig) -> CursorShapeConfig {
+        // Mode entries are added on top of the defaults, so a user setting one
+        // mode keeps the built-in vi mappings
+        let mut modes = defaults.modes.clone();
+        if let Some(partial_modes) = self.modes {
+            modes.extend(partial_modes);
+        }
+        CursorShapeConfig {
+            normal: self.normal.or(defaults.normal),
+            insert: self.insert.or(defaults.insert),
+            selection: self.selection.or(defaults.selection),
+            modes,
+        }
+    }
+}
+
+impl PartialClipboardConfig {
+    pub fn resolve(self, defaults: &ClipboardConfig) -> ClipboardConfig {
+        ClipboardConfig {
+            history_size: self.history_size.unwrap_or(defaults.history_size),
+            history_max_bytes: self.history_max_bytes.unwrap_or(defaults.history_max_bytes),
+            record_external_pastes: self
+                .record_external_pastes
+                .unwrap_or(defaults.record_external_pastes),
+            save_history_in_session: self
+                .save_history_in_session
+                .unwrap_or(defaults.save_history_in_session),
+            reindent_pastes: self.reindent_pastes.unwrap_or(defaults.reindent_pastes),
+            system_timeout_ms: self.system_timeout_ms.unwrap_or(defaults.system_timeout_ms),
+        }
+    }
+}
+
 impl From<&LanguageConfig> for PartialLanguageConfig {
     fn from(cfg: &LanguageConfig) -> Self {
         Self {
@@ -478,6 +1243,14 @@ impl From<&LanguageConfig> for PartialLanguageConfig {
             formatter: cfg.formatter.clone(),
             format_on_save: Some(cfg.format_on_save),
             on_save: Some(cfg.on_save.clone()),
+            save_actions: Some(cfg.save_actions.clone()),
+            save_actions_on_failure: Some(cfg.save_actions_on_failure),
+            save_actions_timeout_ms: Some(cfg.save_actions_timeout_ms),
+            indent_rules: cfg.indent_rules.clone(),
+            on_type_formatting: Some(cfg.on_type_formatting),
+            rulers: cfg.rulers.clone(),
+            auto_complete: Some(cfg.auto_complete),
+            completion_trigger_characters: Some(cfg.completion_trigger_characters.clone()),
         }
     }
 }
@@ -506,6 +1279,24 @@ impl PartialLanguageConfig {
             formatter: self.formatter.or_else(|| defaults.formatter.clone()),
             format_on_save: self.format_on_save.unwrap_or(defaults.format_on_save),
             on_save: self.on_save.unwrap_or_else(|| defaults.on_save.clone()),
+            save_actions: self
+                .save_actions
+                .unwrap_or_else(|| defaults.save_actions.clone()),
+            save_actions_on_failure: self
+                .save_actions_on_failure
+                .unwrap_or(defaults.save_actions_on_failure),
+            save_actions_timeout_ms: self
+                .save_actions_timeout_ms
+                .unwrap_or(defaults.save_actions_timeout_ms),
+            indent_rules: self.indent_rules.or_else(|| defaults.indent_rules.clone()),
+            on_type_formatting: self
+                .on_type_formatting
+                .unwrap_or(defaults.on_type_formatting),
+            rulers: self.rulers.or_else(|| defaults.rulers.clone()),
+            auto_complete: self.auto_complete.unwrap_or(defaults.auto_complete),
+            completion_trigger_characters: self
+                .completion_trigger_characters
+                .unwrap_or_else(|| defaults.completion_trigger_characters.clone()),
         }
     }
 }
@@ -521,6 +1312,7 @@ impl From<&crate::config::Config> for PartialConfig {
             file_explorer: Some(PartialFileExplorerConfig::from(&cfg.file_explorer)),
             file_browser: Some(PartialFileBrowserConfig::from(&cfg.file_browser)),
             terminal: Some(PartialTerminalConfig::from(&cfg.terminal)),
+            mouse: Some(PartialMouseConfig::from(&cfg.mouse)),
             keybindings: Some(cfg.keybindings.clone()),
             keybinding_maps: Some(cfg.keybinding_maps.clone()),
             active_keybinding_map: Some(cfg.active_keybinding_map.clone()),
@@ -532,6 +1324,17 @@ impl From<&crate::config::Config> for PartialConfig {
             ),
             lsp: Some(cfg.lsp.clone()),
             warnings: Some(PartialWarningsConfig::from(&cfg.warnings)),
+            todos: Some(PartialTodosConfig::from(&cfg.todos)),
+            clipboard: Some(PartialClipboardConfig::from(&cfg.clipboard)),
+            files: Some(PartialFilesConfig::from(&cfg.files)),
+            distraction_free: Some(PartialDistractionFreeConfig::from(&cfg.distraction_free)),
+            search: Some(PartialSearchConfig::from(&cfg.search)),
+            session: Some(PartialSessionSnapshotConfig::from(&cfg.session)),
+            updates: Some(PartialUpdatesConfig::from(&cfg.updates)),
+            stats: Some(PartialStatsConfig::from(&cfg.stats)),
+            log: Some(PartialLogConfig::from(&cfg.log)),
+            performance: Some(PartialPerformanceConfig::from(&cfg.performance)),
+            ui: Some(PartialUiConfig::from(&cfg.ui)),
         }
     }
 }
@@ -602,6 +1405,10 @@ impl PartialConfig {
                 .terminal
                 .map(|e| e.resolve(&defaults.terminal))
                 .unwrap_or_else(|| defaults.terminal.clone()),
+            mouse: self
+                .mouse
+                .map(|e| e.resolve(&defaults.mouse))
+                .unwrap_or_else(|| defaults.mouse.clone()),
             keybindings: self
                 .keybindings
                 .unwrap_or_else(|| defaults.keybindings.clone()),
@@ -615,6 +1422,50 @@ impl PartialConfig {
                 .warnings
                 .map(|e| e.resolve(&defaults.warnings))
                 .unwrap_or_else(|| defaults.warnings.clone()),
+            todos: self
+                .todos
+                .map(|e| e.resolve(&defaults.todos))
+                .unwrap_or_else(|| defaults.todos.clone()),
+            clipboard: self
+                .clipboard
+                .map(|e| e.resolve(&defaults.clipboard))
+                .unwrap_or_else(|| defaults.clipboard.clone()),
+            files: self
+                .files
+                .map(|e| e.resolve(&defaults.files))
+                .unwrap_or_else(|| defaults.files.clone()),
+            distraction_free: self
+                .distraction_free
+                .map(|e| e.resolve(&defaults.distraction_free))
+                .unwrap_or_else(|| defaults.distraction_free.clone()),
+            search: self
+                .search
+                .map(|e| e.resolve(&defaults.search))
+                .unwrap_or_else(|| defaults.search.clone()),
+            session: self
+                .session
+                .map(|e| e.resolve(&defaults.session))
+                .unwrap_or_else(|| defaults.session.clone()),
+            updates: self
+                .updates
+                .map(|e| e.resolve(&defaults.updates))
+                .unwrap_or_else(|| defaults.updates.clone()),
+            stats: self
+                .stats
+                .map(|e| e.resolve(&defaults.stats))
+                .unwrap_or_else(|| defaults.stats.clone()),
+            log: self
+                .log
+                .map(|e| e.resolve(&defaults.log))
+                .unwrap_or_else(|| defaults.log.clone()),
+            performance: self
+                .performance
+                .map(|e| e.resolve(&defaults.performance))
+                .unwrap_or_else(|| defaults.performance.clone()),
+            ui: self
+                .ui
+                .map(|e| e.resolve(&defaults.ui))
+                .unwrap_or_else(|| defaults.ui.clone()),
         }
     }
 }
@@ -636,6 +1487,14 @@ impl Default for LanguageConfig {
             formatter: None,
             format_on_save: false,
             on_save: Vec::new(),
+            indent_rules: None,
+            on_type_formatting: false,
+            rulers: None,
+            auto_complete: true,
+            completion_trigger_characters: Vec::new(),
+            save_actions: Vec::new(),
+            save_actions_on_failure: SaveActionFailure::Continue,
+            save_actions_timeout_ms: 5000,
         }
     }
 }
@@ -795,6 +1654,35 @@ mod tests {
         assert!(langs.contains_key("python"));
     }
 
+    #[test]
+    fn merge_protected_globs_adds_up() {
+        let globs = |list: &[&str]| Some(list.iter().map(|g| g.to_string()).collect());
+        let mut project = PartialFilesConfig {
+            protected_globs: globs(&["secrets/**", "!**/.env.test", "**/*.pem"]),
+            ..Default::default()
+        };
+        let user = PartialFilesConfig {
+            protected_globs: globs(&["**/*.pem", "**/.env.test"]),
+            ..Default::default()
+        };
+
+        project.merge_from(&user);
+
+        // The user's first, then the project's, so its negation comes last
+        assert_eq!(
+            project.protected_globs,
+            globs(&["**/.env.test", "secrets/**", "!**/.env.test", "**/*.pem"])
+        );
+
+        // The built-in globs come before all of them, once
+        let resolved = project.resolve(&FilesConfig::default());
+        let defaults = FilesConfig::default().protected_globs;
+        assert_eq!(resolved.protected_globs[..defaults.len()], defaults[..]);
+        assert_eq!(resolved.protected_globs.len(), defaults.len() + 4);
+        let again = PartialFilesConfig::from(&resolved).resolve(&FilesConfig::default());
+        assert_eq!(again.protected_globs, resolved.protected_globs);
+    }
+
     #[test]
     fn merge_languages_same_key_higher_wins() {
         let mut higher = PartialConfig {
@@ -5,6 +5,7 @@
 
 use crate::primitives::display_width::{char_width, str_width};
 use ratatui::style::{Color, Modifier, Style};
+use std::ops::Range;
 
 /// Standard ANSI colors (codes 30-37 for fg, 40-47 for bg)
 const STANDARD_COLORS: [Color; 8] = [
@@ -357,6 +358,99 @@ pub fn parse_ansi_string(text: &str) -> Vec<(String, Style)> {
     result
 }
 
+/// Most styled spans kept on one line; the rest of the line is left plain
+pub const MAX_SPANS_PER_LINE: usize = 128;
+
+/// A run of stripped text drawn with one style
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnsiSpan {
+    /// Byte range in the stripped text
+    pub range: Range<usize>,
+    pub style: Style,
+}
+
+/// Incremental stripper turning text with ANSI escape sequences into plain
+/// text plus styled spans
+///
+/// Feed text in any number of pieces with [`push`](Self::push): the style
+/// and any escape sequence cut off at the end of a piece carry over to the
+/// next, and span offsets count from the start of the first piece. Only SGR
+/// sequences affect the style; cursor movement, erase and other sequences
+/// are dropped.
+#[derive(Debug, Clone, Default)]
+pub struct AnsiStripper {
+    parser: AnsiParser,
+    /// Length of the stripped text produced so far
+    offset: usize,
+    /// Spans started on the current line
+    line_spans: usize,
+}
+
+impl AnsiStripper {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Strip the escape sequences from `text`, returning the visible text and
+    /// the styled spans within it
+    pub fn push(&mut self, text: &str) -> (String, Vec<AnsiSpan>) {
+        let mut stripped = String::with_capacity(text.len());
+        let mut spans: Vec<AnsiSpan> = Vec::new();
+        // Whether the last span may still grow
+        let mut span_open = false;
+
+        for ch in text.chars() {
+            let Some(style) = self.parser.parse_char(ch) else {
+                continue;
+            };
+            let start = self.offset;
+            stripped.push(ch);
+            self.offset += ch.len_utf8();
+
+            if ch == '\n' {
+                self.line_spans = 0;
+                span_open = false;
+                continue;
+            }
+            let Some(style) = visible_style(style) else {
+                span_open = false;
+                continue;
+            };
+            if let Some(span) = spans
+                .last_mut()
+                .filter(|span| span_open && span.style == style)
+            {
+                span.range.end = self.offset;
+            } else if self.line_spans < MAX_SPANS_PER_LINE {
+                spans.push(AnsiSpan {
+                    range: start..self.offset,
+                    style,
+                });
+                self.line_spans += 1;
+                span_open = true;
+            } else {
+                span_open = false;
+            }
+        }
+        (stripped, spans)
+    }
+}
+
+/// The part of a parsed style that changes how text looks, if any
+///
+/// Colors reset to the default and attributes switched off leave nothing to
+/// draw.
+fn visible_style(style: Style) -> Option<Style> {
+    let mut visible = Style::default().add_modifier(style.add_modifier);
+    if let Some(fg) = style.fg.filter(|c| *c != Color::Reset) {
+        visible = visible.fg(fg);
+    }
+    if let Some(bg) = style.bg.filter(|c| *c != Color::Reset) {
+        visible = visible.bg(bg);
+    }
+    (visible != Style::default()).then_some(visible)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -477,4 +571,110 @@ mod tests {
         assert_eq!(segments[1].0, "Red");
         assert_eq!(segments[2].0, " Normal");
     }
+
+    fn strip(text: &str) -> (String, Vec<AnsiSpan>) {
+        AnsiStripper::new().push(text)
+    }
+
+    #[test]
+    fn test_stripper_offsets_refer_to_stripped_text() {
+        let (text, spans) = strip("ok \x1b[32mpassed\x1b[0m done");
+
+        assert_eq!(text, "ok passed done");
+        assert_eq!(
+            spans,
+            vec![AnsiSpan {
+                range: 3..9,
+                style: Style::default().fg(Color::Green),
+            }]
+        );
+        assert_eq!(&text[spans[0].range.clone()], "passed");
+    }
+
+    #[test]
+    fn test_stripper_nested_attributes() {
+        let (text, spans) = strip("\x1b[1mB\x1b[31mBR\x1b[22mR\x1b[39m-\x1b[0mN");
+
+        assert_eq!(text, "BBRR-N");
+        let bold = Style::default().add_modifier(Modifier::BOLD);
+        assert_eq!(
+            spans,
+            vec![
+                AnsiSpan {
+                    range: 0..1,
+                    style: bold,
+                },
+                AnsiSpan {
+                    range: 1..3,
+                    style: bold.fg(Color::Red),
+                },
+                AnsiSpan {
+                    range: 3..4,
+                    style: Style::default().fg(Color::Red),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_stripper_reset_forms() {
+        // "ESC[m", "ESC[0m" and default colors all end the span
+        for reset in ["\x1b[m", "\x1b[0m", "\x1b[39;49m"] {
+            let (text, spans) = strip(&format!("\x1b[33;44mwarn{}plain", reset));
+            assert_eq!(text, "warnplain");
+            assert_eq!(spans.len(), 1, "reset {:?}", reset);
+            assert_eq!(spans[0].range, 0..4);
+            assert_eq!(
+                spans[0].style,
+                Style::default().fg(Color::Yellow).bg(Color::Blue)
+            );
+        }
+    }
+
+    #[test]
+    fn test_stripper_extended_colors() {
+        let (text, spans) = strip("\x1b[38;5;208mA\x1b[48;2;10;20;30mB\x1b[0m");
+
+        assert_eq!(text, "AB");
+        assert_eq!(spans[0].style, Style::default().fg(Color::Indexed(208)));
+        assert_eq!(
+            spans[1].style,
+            Style::default()
+                .fg(Color::Indexed(208))
+                .bg(Color::Rgb(10, 20, 30))
+        );
+    }
+
+    #[test]
+    fn test_stripper_drops_other_sequences() {
+        let (text, spans) = strip("\x1b[2K\x1b[1A\x1b[?25lprogress\x1b]0;title\x07\r\n");
+
+        assert_eq!(text, "progress\r\n");
+        assert!(spans.is_empty());
+    }
+
+    #[test]
+    fn test_stripper_continues_across_pieces() {
+        let mut stripper = AnsiStripper::new();
+        let (first, first_spans) = stripper.push("a\x1b[3");
+        let (second, second_spans) = stripper.push("1mred\nstill red\x1b[0m");
+
+        assert_eq!(first, "a");
+        assert!(first_spans.is_empty());
+        assert_eq!(second, "red\nstill red");
+        // Spans stop at line ends and count from the start of the first piece
+        let ranges: Vec<_> = second_spans.iter().map(|s| s.range.clone()).collect();
+        assert_eq!(ranges, vec![1..4, 5..14]);
+    }
+
+    #[test]
+    fn test_stripper_caps_spans_per_line() {
+        let line: String = (0..MAX_SPANS_PER_LINE + 10)
+            .map(|i| format!("\x1b[3{}mx", i % 2 + 1))
+            .collect();
+        let (_, spans) = strip(&format!("{}\n\x1b[32mnext", line));
+
+        assert_eq!(spans.len(), MAX_SPANS_PER_LINE + 1);
+        assert_eq!(spans.last().unwrap().style.fg, Some(Color::Green));
+    }
 }
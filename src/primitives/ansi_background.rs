@@ -93,7 +93,7 @@ fn blend_channel(fg: u8, bg: u8, opacity: f32) -> u8 {
         .clamp(0.0, 255.0) as u8
 }
 
-fn color_to_rgb(color: Color) -> Option<(u8, u8, u8)> {
+pub(crate) fn color_to_rgb(color: Color) -> Option<(u8, u8, u8)> {
     match color {
         Color::Rgb(r, g, b) => Some((r, g, b)),
         Color::Black => Some((0, 0, 0)),
@@ -0,0 +1,220 @@
+//! Case matching of search patterns ("smartcase")
+//!
+//! In-buffer search, project search, the fuzzy matcher and word completion
+//! all decide whether a pattern matches case-sensitively the same way, with
+//! [`CaseMatching::is_case_sensitive`]. Under [`CaseMatching::Smart`] a
+//! pattern of lowercase letters matches either case, and any uppercase letter
+//! in it makes the match case-sensitive, as with vim's `smartcase`.
+//!
+//! [`preserve_case`] adapts a replacement to the case of the text it
+//! replaces, for replacing with case preserved.
+
+/// How a pattern matches the case of the text
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CaseMatching {
+    Sensitive,
+    Insensitive,
+    /// Case-sensitive only if the pattern has an uppercase letter
+    Smart,
+}
+
+impl CaseMatching {
+    /// Case matching of a search whose smart case setting is `smart_case`,
+    /// falling back to the plain `case_sensitive` toggle when it is off
+    pub fn from_settings(smart_case: bool, case_sensitive: bool) -> Self {
+        match (smart_case, case_sensitive) {
+            (true, _) => Self::Smart,
+            (false, true) => Self::Sensitive,
+            (false, false) => Self::Insensitive,
+        }
+    }
+
+    /// Whether `pattern` matches case-sensitively; `regex` tells whether it
+    /// is a regular expression, whose escapes such as `\S` or `\p{Lu}` are not
+    /// letters to match
+    pub fn is_case_sensitive(self, pattern: &str, regex: bool) -> bool {
+        match self {
+            Self::Sensitive => true,
+            Self::Insensitive => false,
+            Self::Smart => has_uppercase(pattern, regex),
+        }
+    }
+}
+
+/// Whether `pattern` has an uppercase letter to match, leaving out the
+/// escapes, classes and group names of a regular expression when `regex`
+pub fn has_uppercase(pattern: &str, regex: bool) -> bool {
+    if !regex {
+        return pattern.chars().any(char::is_uppercase);
+    }
+    let mut chars = pattern.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                // `\p{Lu}`, `\x{41}`, `\u{41}` and friends: skip the braces
+                Some('p' | 'P' | 'x' | 'u' | 'U') if chars.peek() == Some(&'{') => {
+                    for c in chars.by_ref() {
+                        if c == '}' {
+                            break;
+                        }
+                    }
+                }
+                // `\pL`, `\x41`: the class letter or hex digits
+                Some('p' | 'P') => {
+                    chars.next();
+                }
+                Some('x') => {
+                    chars.next();
+                    chars.next();
+                }
+                // `\S`, `\W`, `\D`, `\B`, `\A`, `\Z` are classes and anchors;
+                // an escaped letter otherwise is a letter
+                Some(c) if c.is_uppercase() && !"SWDBAZ".contains(c) => return true,
+                _ => {}
+            },
+            // Group names and flags, as in `(?P<Name>…)` and `(?-i:…)`
+            '(' if chars.peek() == Some(&'?') => {
+                chars.next();
+                if matches!(chars.peek(), Some('P' | '<')) {
+                    for c in chars.by_ref() {
+                        if c == '>' {
+                            break;
+                        }
+                    }
+                }
+            }
+            c if c.is_uppercase() => return true,
+            _ => {}
+        }
+    }
+    false
+}
+
+/// Whether `candidate` starts with `prefix` in the same case, for ranking
+/// completions that match the typed case first
+pub fn prefix_matches_case(candidate: &str, prefix: &str) -> bool {
+    candidate.starts_with(prefix)
+}
+
+/// `replacement` in the case of the text it replaces, `matched`
+///
+/// - All uppercase (`FOO`, two or more letters): uppercased
+/// - Capitalized (`Foo`, or a single uppercase letter): first letter
+///   uppercased
+/// - All lowercase (`foo`): first letter lowercased
+/// - Anything else (`fOo`, `FooBar`, no letters): as typed
+pub fn preserve_case(matched: &str, replacement: &str) -> String {
+    let cased: Vec<char> = matched
+        .chars()
+        .filter(|c| c.is_uppercase() || c.is_lowercase())
+        .collect();
+    let Some((first, rest)) = cased.split_first() else {
+        return replacement.to_string();
+    };
+    let rest_upper = rest.iter().all(|c| c.is_uppercase());
+    let rest_lower = rest.iter().all(|c| c.is_lowercase());
+    if first.is_uppercase() && rest_upper && !rest.is_empty() {
+        replacement.to_uppercase()
+    } else if first.is_uppercase() && rest_lower {
+        map_first_letter(replacement, |c| c.to_uppercase().collect())
+    } else if first.is_lowercase() && rest_lower {
+        map_first_letter(replacement, |c| c.to_lowercase().collect())
+    } else {
+        replacement.to_string()
+    }
+}
+
+/// `text` with its first cased letter mapped by `f`
+fn map_first_letter(text: &str, f: impl Fn(char) -> String) -> String {
+    match text
+        .char_indices()
+        .find(|(_, c)| c.is_uppercase() || c.is_lowercase())
+    {
+        Some((i, c)) => format!("{}{}{}", &text[..i], f(c), &text[i + c.len_utf8()..]),
+        None => text.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fixed_modes_ignore_the_pattern() {
+        for pattern in ["foo", "Foo", ""] {
+            assert!(CaseMatching::Sensitive.is_case_sensitive(pattern, false));
+            assert!(!CaseMatching::Insensitive.is_case_sensitive(pattern, false));
+        }
+        assert_eq!(
+            CaseMatching::from_settings(true, false),
+            CaseMatching::Smart
+        );
+        assert_eq!(
+            CaseMatching::from_settings(false, true),
+            CaseMatching::Sensitive
+        );
+        assert_eq!(
+            CaseMatching::from_settings(false, false),
+            CaseMatching::Insensitive
+        );
+    }
+
+    #[test]
+    fn test_smart_case_literal_patterns() {
+        let smart = CaseMatching::Smart;
+        assert!(!smart.is_case_sensitive("foo", false));
+        assert!(!smart.is_case_sensitive("", false));
+        assert!(!smart.is_case_sensitive("foo_bar 42!", false));
+        assert!(smart.is_case_sensitive("Foo", false));
+        assert!(smart.is_case_sensitive("fooBar", false));
+        assert!(smart.is_case_sensitive("ÉCOLE", false));
+        assert!(!smart.is_case_sensitive("école", false));
+        // Scripts without case never make a search case-sensitive
+        assert!(!smart.is_case_sensitive("日本語", false));
+        // A literal backslash is just text
+        assert!(smart.is_case_sensitive(r"\S", false));
+    }
+
+    #[test]
+    fn test_smart_case_regex_patterns() {
+        let smart = CaseMatching::Smart;
+        assert!(!smart.is_case_sensitive(r"foo\s+bar", true));
+        assert!(!smart.is_case_sensitive(r"\S+\W\D\b\B\A\Z", true));
+        assert!(!smart.is_case_sensitive(r"\p{Lu}+\P{L}\pL", true));
+        assert!(!smart.is_case_sensitive(r"\x{41}\x41\u{42}", true));
+        assert!(!smart.is_case_sensitive(r"(?P<Name>foo)(?<Other>bar)", true));
+        assert!(smart.is_case_sensitive(r"Foo\s+", true));
+        assert!(smart.is_case_sensitive(r"[A-Z]+", true));
+        assert!(smart.is_case_sensitive(r"\p{Lu}Bar", true));
+        assert!(smart.is_case_sensitive(r"(?P<name>Foo)", true));
+    }
+
+    #[test]
+    fn test_prefix_matches_case() {
+        assert!(prefix_matches_case("FooBar", "Foo"));
+        assert!(!prefix_matches_case("foobar", "Foo"));
+        assert!(prefix_matches_case("foobar", ""));
+    }
+
+    #[test]
+    fn test_preserve_case() {
+        assert_eq!(preserve_case("foo", "bar"), "bar");
+        assert_eq!(preserve_case("Foo", "bar"), "Bar");
+        assert_eq!(preserve_case("FOO", "bar"), "BAR");
+        assert_eq!(preserve_case("foo", "Bar"), "bar");
+        assert_eq!(preserve_case("F", "bar"), "Bar");
+        assert_eq!(preserve_case("f", "Bar"), "bar");
+        // Only the first letter changes, keeping the replacement's own case
+        assert_eq!(preserve_case("Foo", "barBaz"), "BarBaz");
+        assert_eq!(preserve_case("foo", "BarBaz"), "barBaz");
+        assert_eq!(preserve_case("FOO_BAR", "baz_qux"), "BAZ_QUX");
+        // Mixed case and no letters leave the replacement as typed
+        assert_eq!(preserve_case("fOo", "barBaz"), "barBaz");
+        assert_eq!(preserve_case("FooBar", "bazQux"), "bazQux");
+        assert_eq!(preserve_case("42", "Bar"), "Bar");
+        // Leading non-letters are kept
+        assert_eq!(preserve_case("_foo", "_bar"), "_bar");
+        assert_eq!(preserve_case("_Foo", "_bar"), "_Bar");
+        assert_eq!(preserve_case("École", "ecole"), "Ecole");
+    }
+}
@@ -0,0 +1,125 @@
+//! Column arithmetic for "Paste as Column" and "Align Selections by
+//! Character"
+//!
+//! Columns are visual: a tab advances to the next multiple of the tab size
+//! and wide characters such as CJK take two cells, as the renderer draws
+//! them.
+
+use crate::primitives::display_width::char_width;
+
+/// Width of `c` when it starts at visual column `column`
+fn advance(c: char, column: usize, tab_size: usize) -> usize {
+    if c == '\t' {
+        let tab_size = tab_size.max(1);
+        tab_size - column % tab_size
+    } else {
+        char_width(c)
+    }
+}
+
+/// Visual column where `text`, starting a line, ends
+pub fn column_after(text: &str, tab_size: usize) -> usize {
+    text.chars()
+        .fold(0, |column, c| column + advance(c, column, tab_size))
+}
+
+/// Where text inserted at visual column `column` of `line` goes: the byte
+/// offset of the first character at or past that column, and how many
+/// spaces to insert first so the text starts at the column
+///
+/// A line shorter than `column` is padded at its end. A tab or wide
+/// character covering the column is kept whole, and the text goes after it.
+pub fn insertion_point(line: &str, column: usize, tab_size: usize) -> (usize, usize) {
+    let mut current = 0;
+    for (offset, c) in line.char_indices() {
+        if current >= column {
+            return (offset, 0);
+        }
+        current += advance(c, current, tab_size);
+    }
+    (line.len(), column.saturating_sub(current))
+}
+
+/// Spaces to insert before the first `delimiter` of each line so they all
+/// start at the same visual column
+///
+/// Returns the byte offset of each line's delimiter with the number of
+/// spaces to insert there, or None for a line without the delimiter.
+pub fn alignment_padding(
+    lines: &[&str],
+    delimiter: &str,
+    tab_size: usize,
+) -> Vec<Option<(usize, usize)>> {
+    let found: Vec<Option<(usize, usize)>> = lines
+        .iter()
+        .map(|line| {
+            let offset = line.find(delimiter)?;
+            Some((offset, column_after(&line[..offset], tab_size)))
+        })
+        .collect();
+    let target = found.iter().flatten().map(|&(_, column)| column).max();
+    found
+        .into_iter()
+        .map(|found| {
+            let (offset, column) = found?;
+            Some((offset, target? - column))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_column_after_tabs_and_wide_chars() {
+        assert_eq!(column_after("ab", 4), 2);
+        // A tab goes to the next tab stop
+        assert_eq!(column_after("a\tb", 4), 5);
+        assert_eq!(column_after("abcd\t", 4), 8);
+        // CJK characters take two cells
+        assert_eq!(column_after("名前", 4), 4);
+        assert_eq!(column_after("名\t", 4), 4);
+    }
+
+    #[test]
+    fn test_insertion_point() {
+        assert_eq!(insertion_point("hello", 2, 4), (2, 0));
+        // Short lines are padded at their end
+        assert_eq!(insertion_point("hi", 5, 4), (2, 3));
+        assert_eq!(insertion_point("", 3, 4), (0, 3));
+        // A wide character covering the column is kept whole
+        assert_eq!(insertion_point("名前", 1, 4), (3, 0));
+        // So is a tab
+        assert_eq!(insertion_point("\tx", 2, 4), (1, 0));
+    }
+
+    #[test]
+    fn test_alignment_padding() {
+        let lines = ["a = 1", "long_name = 2", "no delimiter", "b=3"];
+        assert_eq!(
+            alignment_padding(&lines, "=", 4),
+            vec![Some((2, 8)), Some((10, 0)), None, Some((1, 9))]
+        );
+    }
+
+    #[test]
+    fn test_alignment_padding_with_tabs_and_cjk() {
+        // "名前" is four cells wide, the tab ends at column 4
+        let lines = ["名前: 1", "\tx: 2", "ab: 3"];
+        assert_eq!(
+            alignment_padding(&lines, ":", 4),
+            vec![Some((6, 1)), Some((2, 0)), Some((2, 3))]
+        );
+    }
+
+    #[test]
+    fn test_alignment_padding_multi_char_delimiter() {
+        let lines = ["a => 1", "abc => 2"];
+        assert_eq!(
+            alignment_padding(&lines, "=>", 4),
+            vec![Some((2, 2)), Some((4, 0))]
+        );
+        assert_eq!(alignment_padding(&["x"], "=>", 4), vec![None]);
+    }
+}
@@ -0,0 +1,1641 @@
+//! Key paths in JSON, YAML and TOML documents
+//!
+//! [`Outline::parse`] reads a document with a small parser for its format
+//! and records every key and array element as a node: where it starts (its
+//! key, or the element itself for array elements), where its value starts
+//! and where it ends. The path at an offset (`spec.containers[3].name`), the
+//! value around an offset and the position of a path are then cheap lookups.
+//!
+//! The parsers only follow structure; they accept what they need to find
+//! keys and values and stop at the first thing they can't read. Nodes still
+//! open at that point extend to the end of the document, so a malformed
+//! document still gives the path down to the last ancestor that parsed.
+//!
+//! YAML anchors and tags are skipped over and aliases are plain values: a
+//! path names where a key is written, not what a merge key brings in.
+
+use crate::config::DataPathStyle;
+use crate::model::buffer::Buffer;
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::ops::Range;
+use std::path::Path;
+
+/// Largest buffer that is parsed for paths
+pub const MAX_OUTLINE_BYTES: usize = 8 * 1024 * 1024;
+
+/// Deepest nesting the parsers follow
+const MAX_DEPTH: usize = 256;
+
+/// End of a node that is still being parsed
+const OPEN: usize = usize::MAX;
+
+/// Document formats with key paths
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DataFormat {
+    Json,
+    Yaml,
+    Toml,
+}
+
+impl DataFormat {
+    /// Format of a file, from its extension or name
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let name = path.file_name()?.to_str()?;
+        if matches!(name, "Cargo.lock" | "Pipfile" | "poetry.lock") {
+            return Some(DataFormat::Toml);
+        }
+        let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+        match extension.as_str() {
+            "json" | "jsonc" | "geojson" | "webmanifest" => Some(DataFormat::Json),
+            "yaml" | "yml" => Some(DataFormat::Yaml),
+            "toml" => Some(DataFormat::Toml),
+            _ => None,
+        }
+    }
+}
+
+/// One step of a path: a key of a mapping or an index of an array
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum PathSegment {
+    Key(String),
+    Index(usize),
+}
+
+#[derive(Debug, Clone)]
+struct Node {
+    /// Mapping or array the node belongs to, None for a document root
+    parent: Option<usize>,
+    /// Key or index in the parent, None for a document root
+    segment: Option<PathSegment>,
+    /// Start of the key, or of the value for array elements and roots
+    start: usize,
+    value_start: usize,
+    end: usize,
+}
+
+/// Keys and array elements of a document with their positions
+#[derive(Debug, Clone, Default)]
+pub struct Outline {
+    nodes: Vec<Node>,
+    /// Node indices by start, outer nodes first when starts are equal
+    order: Vec<usize>,
+    /// Innermost node whose span contains each node
+    enclosing: Vec<Option<usize>>,
+    /// Where parsing stopped, if the document is malformed
+    error: Option<usize>,
+}
+
+impl Outline {
+    /// Parse `text` as `format`
+    pub fn parse(format: DataFormat, text: &str) -> Self {
+        match format {
+            DataFormat::Json => JsonParser::new(text, Syntax::Json).parse_document(),
+            DataFormat::Yaml => YamlParser::new(text).parse(),
+            DataFormat::Toml => TomlParser::new(text).parse(),
+        }
+    }
+
+    /// Offset at which the document stopped parsing, if it is malformed
+    pub fn error(&self) -> Option<usize> {
+        self.error
+    }
+
+    /// Number of keys and array elements (and document roots)
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Innermost node whose span contains `offset` (the end counts as inside)
+    fn innermost(&self, offset: usize) -> Option<usize> {
+        let after = self
+            .order
+            .partition_point(|&node| self.nodes[node].start <= offset);
+        let mut node = self.order[after.checked_sub(1)?];
+        loop {
+            if offset <= self.nodes[node].end {
+                return Some(node);
+            }
+            node = self.enclosing[node]?;
+        }
+    }
+
+    /// Path of a node from its document root
+    fn path_of(&self, mut node: usize) -> Vec<PathSegment> {
+        let mut path = Vec::new();
+        loop {
+            let current = &self.nodes[node];
+            if let Some(segment) = &current.segment {
+                path.push(segment.clone());
+            }
+            match current.parent {
+                Some(parent) => node = parent,
+                None => break,
+            }
+        }
+        path.reverse();
+        path
+    }
+
+    /// Path of the key or element at `offset`, empty at the document root
+    pub fn path_at(&self, offset: usize) -> Vec<PathSegment> {
+        self.innermost(offset)
+            .map(|node| self.path_of(node))
+            .unwrap_or_default()
+    }
+
+    /// Value to select for the selection `selection`
+    ///
+    /// With nothing selected that is the value of the key or element at the
+    /// cursor. When the selection already covers a value or more, it is the
+    /// value of the enclosing key or element, so repeating the selection
+    /// walks outwards.
+    pub fn value_range_around(&self, selection: Range<usize>) -> Option<Range<usize>> {
+        let mut node = self.innermost(selection.start)?;
+        loop {
+            let current = &self.nodes[node];
+            let value = current.value_start..current.end;
+            let grows =
+                value.start <= selection.start && selection.end <= value.end && value != selection;
+            let at_key = selection.is_empty() && current.start <= selection.start;
+            if (grows || at_key) && !value.is_empty() {
+                return Some(value);
+            }
+            node = self.enclosing[node]?;
+        }
+    }
+
+    /// Start of the key or element at `path`, the first one in the document
+    /// if it occurs more than once
+    pub fn find(&self, path: &[PathSegment]) -> Option<usize> {
+        self.order
+            .iter()
+            .copied()
+            .find(|&node| self.has_path(node, path))
+            .map(|node| self.nodes[node].start)
+    }
+
+    fn has_path(&self, mut node: usize, path: &[PathSegment]) -> bool {
+        let mut remaining = path.len();
+        loop {
+            let current = &self.nodes[node];
+            match &current.segment {
+                Some(segment) => {
+                    if remaining == 0 || path[remaining - 1] != *segment {
+                        return false;
+                    }
+                    remaining -= 1;
+                }
+                None if current.parent.is_none() => return remaining == 0,
+                None => {}
+            }
+            match current.parent {
+                Some(parent) => node = parent,
+                None => return remaining == 0,
+            }
+        }
+    }
+}
+
+/// Collects nodes while a parser runs
+#[derive(Default)]
+struct Builder {
+    nodes: Vec<Node>,
+}
+
+impl Builder {
+    fn open(
+        &mut self,
+        parent: Option<usize>,
+        segment: Option<PathSegment>,
+        start: usize,
+        value_start: usize,
+    ) -> usize {
+        self.nodes.push(Node {
+            parent,
+            segment,
+            start,
+            value_start,
+            end: OPEN,
+        });
+        self.nodes.len() - 1
+    }
+
+    fn close(&mut self, node: usize, end: usize) {
+        let node = &mut self.nodes[node];
+        node.end = end.max(node.value_start);
+    }
+
+    fn set_value_start(&mut self, node: usize, value_start: usize) {
+        self.nodes[node].value_start = value_start;
+    }
+
+    /// Extend nodes left open by an error to `len` and index the nodes
+    fn finish(mut self, len: usize, error: Option<usize>) -> Outline {
+        for node in &mut self.nodes {
+            if node.end == OPEN {
+                node.end = len;
+            }
+        }
+        let nodes = self.nodes;
+        let mut order: Vec<usize> = (0..nodes.len()).collect();
+        order.sort_by_key(|&node| (nodes[node].start, Reverse(nodes[node].end), node));
+
+        let mut enclosing = vec![None; nodes.len()];
+        let mut stack: Vec<usize> = Vec::new();
+        for &node in &order {
+            while let Some(&outer) = stack.last() {
+                if nodes[outer].start <= nodes[node].start && nodes[node].end <= nodes[outer].end {
+                    break;
+                }
+                stack.pop();
+            }
+            enclosing[node] = stack.last().copied();
+            stack.push(node);
+        }
+
+        Outline {
+            nodes,
+            order,
+            enclosing,
+            error,
+        }
+    }
+}
+
+/// Result of a parsing step; the error is the offset where it failed
+type Parsed<T> = Result<T, usize>;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Syntax {
+    Json,
+    /// YAML flow collections: `{a: 1, b: [x, y]}`
+    YamlFlow,
+}
+
+/// JSON (with comments and trailing commas), also used for YAML flow
+/// collections
+struct JsonParser<'a> {
+    text: &'a str,
+    bytes: &'a [u8],
+    pos: usize,
+    syntax: Syntax,
+    builder: Builder,
+}
+
+impl<'a> JsonParser<'a> {
+    fn new(text: &'a str, syntax: Syntax) -> Self {
+        Self {
+            text,
+            bytes: text.as_bytes(),
+            pos: 0,
+            syntax,
+            builder: Builder::default(),
+        }
+    }
+
+    fn parse_document(mut self) -> Outline {
+        self.skip_trivia();
+        let mut error = None;
+        if self.pos < self.bytes.len() {
+            let start = self.pos;
+            match self.value(None, None, start, 0) {
+                Ok(()) => {
+                    self.skip_trivia();
+                    if self.pos < self.bytes.len() {
+                        error = Some(self.pos);
+                    }
+                }
+                Err(at) => error = Some(at),
+            }
+        }
+        self.builder.finish(self.text.len(), error)
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.bytes.get(self.pos).copied()
+    }
+
+    /// Skip whitespace and comments
+    fn skip_trivia(&mut self) {
+        while let Some(byte) = self.peek() {
+            match byte {
+                b' ' | b'\t' | b'\n' | b'\r' => self.pos += 1,
+                b'#' if self.syntax == Syntax::YamlFlow => self.skip_line(),
+                b'/' if self.syntax == Syntax::Json => match self.bytes.get(self.pos + 1) {
+                    Some(b'/') => self.skip_line(),
+                    Some(b'*') => {
+                        self.pos = match self.text[self.pos + 2..].find("*/") {
+                            Some(end) => self.pos + 2 + end + 2,
+                            None => self.bytes.len(),
+                        };
+                    }
+                    _ => return,
+                },
+                _ => return,
+            }
+        }
+    }
+
+    fn skip_line(&mut self) {
+        self.pos = match self.text[self.pos..].find('\n') {
+            Some(newline) => self.pos + newline,
+            None => self.bytes.len(),
+        };
+    }
+
+    fn value(
+        &mut self,
+        parent: Option<usize>,
+        segment: Option<PathSegment>,
+        start: usize,
+        depth: usize,
+    ) -> Parsed<()> {
+        if depth > MAX_DEPTH {
+            return Err(self.pos);
+        }
+        let node = self.builder.open(parent, segment, start, self.pos);
+        match self.peek() {
+            Some(b'{') => {
+                self.pos += 1;
+                self.object(node, depth)?;
+            }
+            Some(b'[') => {
+                self.pos += 1;
+                self.array(node, depth)?;
+            }
+            Some(b'"' | b'\'') => {
+                self.string()?;
+            }
+            Some(_) => self.scalar()?,
+            None => return Err(self.pos),
+        }
+        self.builder.close(node, self.pos);
+        Ok(())
+    }
+
+    fn object(&mut self, node: usize, depth: usize) -> Parsed<()> {
+        loop {
+            self.skip_trivia();
+            match self.peek() {
+                Some(b'}') => {
+                    self.pos += 1;
+                    return Ok(());
+                }
+                Some(_) => {
+                    let key_start = self.pos;
+                    let key = self.key()?;
+                    self.skip_trivia();
+                    if self.peek() != Some(b':') {
+                        return Err(self.pos);
+                    }
+                    self.pos += 1;
+                    self.skip_trivia();
+                    let segment = Some(PathSegment::Key(key));
+                    if self.syntax == Syntax::YamlFlow && matches!(self.peek(), Some(b',' | b'}')) {
+                        // `{a, b: }`: a key without a value
+                        let empty = self.builder.open(Some(node), segment, key_start, self.pos);
+                        self.builder.close(empty, self.pos);
+                    } else {
+                        self.value(Some(node), segment, key_start, depth + 1)?;
+                    }
+                    self.skip_trivia();
+                    match self.peek() {
+                        Some(b',') => self.pos += 1,
+                        Some(b'}') => {}
+                        _ => return Err(self.pos),
+                    }
+                }
+                None => return Err(self.pos),
+            }
+        }
+    }
+
+    fn array(&mut self, node: usize, depth: usize) -> Parsed<()> {
+        let mut index = 0;
+        loop {
+            self.skip_trivia();
+            match self.peek() {
+                Some(b']') => {
+                    self.pos += 1;
+                    return Ok(());
+                }
+                Some(_) => {
+                    let start = self.pos;
+                    self.value(
+                        Some(node),
+                        Some(PathSegment::Index(index)),
+                        start,
+                        depth + 1,
+                    )?;
+                    index += 1;
+                    self.skip_trivia();
+                    match self.peek() {
+                        Some(b',') => self.pos += 1,
+                        Some(b']') => {}
+                        _ => return Err(self.pos),
+                    }
+                }
+                None => return Err(self.pos),
+            }
+        }
+    }
+
+    fn key(&mut self) -> Parsed<String> {
+        match self.peek() {
+            Some(b'"') => self.string(),
+            Some(b'\'') if self.syntax == Syntax::YamlFlow => self.string(),
+            Some(_) if self.syntax == Syntax::YamlFlow => {
+                let start = self.pos;
+                while let Some(byte) = self.peek() {
+                    if matches!(byte, b':' | b',' | b'{' | b'}' | b'[' | b']' | b'\n') {
+                        break;
+                    }
+                    self.pos += 1;
+                }
+                let key = self.text[start..self.pos].trim();
+                if key.is_empty() {
+                    return Err(start);
+                }
+                Ok(key.to_string())
+            }
+            _ => Err(self.pos),
+        }
+    }
+
+    /// A quoted string, returned unescaped
+    fn string(&mut self) -> Parsed<String> {
+        let start = self.pos;
+        let end = quoted_end(self.bytes, start, self.syntax == Syntax::Json).ok_or(start)?;
+        self.pos = end;
+        Ok(unquote(&self.text[start..end]))
+    }
+
+    /// Numbers, literals and YAML plain scalars
+    fn scalar(&mut self) -> Parsed<()> {
+        let start = self.pos;
+        let mut end = start;
+        while let Some(byte) = self.peek() {
+            let stop = match self.syntax {
+                Syntax::Json => {
+                    matches!(byte, b',' | b']' | b'}' | b':' | b'/') || byte.is_ascii_whitespace()
+                }
+                Syntax::YamlFlow => {
+                    matches!(byte, b',' | b']' | b'}' | b'\n')
+                        || (byte == b'#' && self.pos > start && self.bytes[self.pos - 1] == b' ')
+                }
+            };
+            if stop {
+                break;
+            }
+            self.pos += 1;
+            if !byte.is_ascii_whitespace() {
+                end = self.pos;
+            }
+        }
+        if end == start {
+            return Err(start);
+        }
+        self.pos = end;
+        Ok(())
+    }
+}
+
+/// End (after the closing quote) of the string starting at `start`
+///
+/// Single-line strings stop at a newline, so an unterminated one doesn't
+/// take the rest of the document with it.
+fn quoted_end(bytes: &[u8], start: usize, single_line: bool) -> Option<usize> {
+    let quote = *bytes.get(start)?;
+    let mut pos = start + 1;
+    while let Some(&byte) = bytes.get(pos) {
+        match byte {
+            b'\\' if quote == b'"' => pos += 2,
+            b'\n' if single_line => return None,
+            _ if byte == quote => {
+                // YAML single quotes escape themselves by doubling
+                if quote == b'\'' && bytes.get(pos + 1) == Some(&b'\'') {
+                    pos += 2;
+                    continue;
+                }
+                return Some(pos + 1);
+            }
+            _ => pos += 1,
+        }
+    }
+    None
+}
+
+/// Text of a quoted key or string
+fn unquote(quoted: &str) -> String {
+    if quoted.starts_with('\'') {
+        return quoted[1..quoted.len() - 1].replace("''", "'");
+    }
+    serde_json::from_str(quoted).unwrap_or_else(|_| quoted[1..quoted.len() - 1].to_string())
+}
+
+/// Whether a YAML line is a sequence item (`- x` or a lone `-`)
+fn is_item(content: &str) -> bool {
+    content == "-" || content.starts_with("- ")
+}
+
+/// YAML line content with a trailing comment removed
+fn strip_comment(content: &str) -> &str {
+    let bytes = content.as_bytes();
+    let mut quote = None;
+    for (i, &byte) in bytes.iter().enumerate() {
+        match quote {
+            Some(q) if byte == q => quote = None,
+            Some(_) => {}
+            None => match byte {
+                b'"' | b'\'' if i == 0 || matches!(bytes[i - 1], b' ' | b':' | b'[' | b'{') => {
+                    quote = Some(byte)
+                }
+                b'#' if i == 0 || bytes[i - 1] == b' ' => return content[..i].trim_end(),
+                _ => {}
+            },
+        }
+    }
+    content.trim_end()
+}
+
+/// Key of a YAML `key: value` line and the length up to and including its
+/// colon
+fn yaml_key(content: &str) -> Option<(String, usize)> {
+    let bytes = content.as_bytes();
+    let (key, after_key) = match bytes.first()? {
+        b'"' | b'\'' => {
+            let end = quoted_end(bytes, 0, true)?;
+            (unquote(&content[..end]), end)
+        }
+        b'[' | b'{' | b'#' | b'&' | b'*' | b'!' | b'|' | b'>' | b'%' | b'@' | b'`' | b'?' => {
+            return None
+        }
+        _ => {
+            let colon = content
+                .match_indices(':')
+                .map(|(i, _)| i)
+                .find(|&i| matches!(bytes.get(i + 1), None | Some(b' ' | b'\t')))?;
+            let key = content[..colon].trim_end();
+            if key.is_empty() {
+                return None;
+            }
+            return Some((key.to_string(), colon + 1));
+        }
+    };
+    let rest = &content[after_key..];
+    let spaces = rest.len() - rest.trim_start().len();
+    let rest = &rest[spaces..];
+    if rest.starts_with(':') && matches!(rest.as_bytes().get(1), None | Some(b' ' | b'\t')) {
+        Some((key, after_key + spaces + 1))
+    } else {
+        None
+    }
+}
+
+/// Length of the anchors and tags (`&base !!map `) before a YAML value
+fn yaml_properties_len(content: &str) -> usize {
+    let mut rest = content;
+    while rest.starts_with('&') || rest.starts_with('!') {
+        let token = rest.find([' ', '\t']).unwrap_or(rest.len());
+        rest = rest[token..].trim_start();
+    }
+    content.len() - rest.len()
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum FrameKind {
+    /// A key or item whose value hasn't started yet
+    Pending,
+    Map,
+    Seq,
+    /// A scalar or flow value; deeper lines continue it
+    Value,
+}
+
+/// A YAML node whose value may continue on following lines
+struct Frame {
+    node: usize,
+    kind: FrameKind,
+    /// Column of the keys or items of a collection; for other frames the
+    /// column of the key or item owning the value (-1 for a document root)
+    indent: isize,
+    /// Items of a sequence so far
+    count: usize,
+}
+
+/// YAML block structure, line by line
+struct YamlParser<'a> {
+    text: &'a str,
+    builder: Builder,
+    stack: Vec<Frame>,
+    /// End of the content of the last line that belonged to the document
+    last_end: usize,
+    /// Flow collections can span lines; lines before this were read already
+    skip_until: usize,
+}
+
+impl<'a> YamlParser<'a> {
+    fn new(text: &'a str) -> Self {
+        Self {
+            text,
+            builder: Builder::default(),
+            stack: Vec::new(),
+            last_end: 0,
+            skip_until: 0,
+        }
+    }
+
+    fn parse(mut self) -> Outline {
+        self.start_document(0);
+        let mut error = None;
+        let mut line_start = 0;
+        while line_start < self.text.len() {
+            let line_end = self.text[line_start..]
+                .find('\n')
+                .map_or(self.text.len(), |newline| line_start + newline);
+            if let Err(at) = self.line(line_start, line_end) {
+                error = Some(at);
+                break;
+            }
+            line_start = line_end + 1;
+        }
+        if error.is_none() {
+            self.close_frames(0);
+        }
+        self.builder.finish(self.text.len(), error)
+    }
+
+    fn start_document(&mut self, start: usize) {
+        let root = self.builder.open(None, None, start, start);
+        self.stack.push(Frame {
+            node: root,
+            kind: FrameKind::Pending,
+            indent: -1,
+            count: 0,
+        });
+    }
+
+    /// Close the frames above the first `keep`
+    fn close_frames(&mut self, keep: usize) {
+        while self.stack.len() > keep {
+            let frame = self.stack.pop().unwrap();
+            self.builder.close(frame.node, self.last_end);
+        }
+    }
+
+    fn line(&mut self, start: usize, end: usize) -> Parsed<()> {
+        if end <= self.skip_until {
+            return Ok(());
+        }
+        let raw = self.text[start..end].trim_end_matches('\r');
+        let col = raw.len() - raw.trim_start_matches([' ', '\t']).len();
+        let pos = start + col;
+
+        // Block scalars and multi-line plain scalars take deeper lines as is
+        if let Some(top) = self.stack.last() {
+            if top.kind == FrameKind::Value && (col as isize) > top.indent {
+                if raw.trim().is_empty() {
+                    return Ok(());
+                }
+                self.last_end = start + raw.len();
+                return Ok(());
+            }
+        }
+
+        let content = strip_comment(&raw[col..]);
+        if content.is_empty() {
+            return Ok(());
+        }
+
+        if col == 0 && (content == "---" || content.starts_with("--- ") || content == "...") {
+            self.close_frames(0);
+            if content != "..." {
+                self.start_document(start);
+            }
+            self.last_end = pos + content.len();
+            return Ok(());
+        }
+        if col == 0 && content.starts_with('%') {
+            // Directive
+            return Ok(());
+        }
+
+        self.close_finished(col, content);
+        if self.stack.is_empty() {
+            // Content after `...` without `---`
+            self.start_document(start);
+        }
+        self.entry(pos, col, content)?;
+        self.last_end = self.last_end.max(pos + content.len());
+        Ok(())
+    }
+
+    /// Close the frames a line at `col` no longer belongs to
+    fn close_finished(&mut self, col: usize, content: &str) {
+        let col = col as isize;
+        let item = is_item(content);
+        let mut keep = self.stack.len();
+        while keep > 0 {
+            let frame = &self.stack[keep - 1];
+            let stays = match frame.kind {
+                FrameKind::Pending => {
+                    col > frame.indent
+                        || (col == frame.indent
+                            && item
+                            && keep >= 2
+                            && self.stack[keep - 2].kind == FrameKind::Map)
+                }
+                FrameKind::Map => col > frame.indent || (col == frame.indent && !item),
+                FrameKind::Seq => col > frame.indent || (col == frame.indent && item),
+                FrameKind::Value => col > frame.indent,
+            };
+            if stays {
+                break;
+            }
+            keep -= 1;
+        }
+        self.close_frames(keep);
+    }
+
+    /// Read the content of a line (or what follows `- ` on it) at `col`
+    fn entry(&mut self, pos: usize, col: usize, content: &str) -> Parsed<()> {
+        if self.stack.len() > MAX_DEPTH {
+            return Err(pos);
+        }
+        let Some(top) = self.stack.last_mut() else {
+            return Err(pos);
+        };
+        if top.kind == FrameKind::Pending {
+            let node = top.node;
+            if is_item(content) {
+                top.kind = FrameKind::Seq;
+                top.indent = col as isize;
+            } else if yaml_key(content).is_some() {
+                top.kind = FrameKind::Map;
+                top.indent = col as isize;
+            } else {
+                return self.inline_value(node, pos, content);
+            }
+            self.builder.set_value_start(node, pos);
+        }
+
+        let top = self.stack.last_mut().unwrap();
+        let container = top.node;
+        match top.kind {
+            FrameKind::Seq => {
+                if !is_item(content) {
+                    return Err(pos);
+                }
+                let index = top.count;
+                top.count += 1;
+                let rest = content[1..].trim_start();
+                let rest_offset = content.len() - rest.len();
+                let item = self.builder.open(
+                    Some(container),
+                    Some(PathSegment::Index(index)),
+                    pos,
+                    pos + rest_offset,
+                );
+                self.stack.push(Frame {
+                    node: item,
+                    kind: FrameKind::Pending,
+                    indent: col as isize,
+                    count: 0,
+                });
+                if rest.is_empty() {
+                    return Ok(());
+                }
+                self.entry(pos + rest_offset, col + rest_offset, rest)
+            }
+            FrameKind::Map => {
+                let Some((key, key_len)) = yaml_key(content) else {
+                    return Err(pos);
+                };
+                let rest = content[key_len..].trim_start();
+                let rest_offset = content.len() - rest.len();
+                let node = self.builder.open(
+                    Some(container),
+                    Some(PathSegment::Key(key)),
+                    pos,
+                    pos + rest_offset,
+                );
+                self.stack.push(Frame {
+                    node,
+                    kind: FrameKind::Pending,
+                    indent: col as isize,
+                    count: 0,
+                });
+                let properties = yaml_properties_len(rest);
+                if properties == rest.len() {
+                    // The value (if any) is on the following lines
+                    return Ok(());
+                }
+                self.inline_value(node, pos + rest_offset + properties, &rest[properties..])
+            }
+            FrameKind::Pending | FrameKind::Value => Err(pos),
+        }
+    }
+
+    /// A value written after its key or dash: a scalar, block scalar header
+    /// or flow collection
+    fn inline_value(&mut self, node: usize, pos: usize, content: &str) -> Parsed<()> {
+        let properties = yaml_properties_len(content);
+        let pos = pos + properties;
+        let content = &content[properties..];
+        self.builder.set_value_start(node, pos);
+        if let Some(frame) = self.stack.last_mut() {
+            frame.kind = FrameKind::Value;
+        }
+        if content.starts_with('{') || content.starts_with('[') {
+            let mut flow = JsonParser::new(self.text, Syntax::YamlFlow);
+            flow.pos = pos;
+            flow.builder = std::mem::take(&mut self.builder);
+            let depth = self.stack.len();
+            let result = if content.starts_with('{') {
+                flow.pos += 1;
+                flow.object(node, depth)
+            } else {
+                flow.pos += 1;
+                flow.array(node, depth)
+            };
+            self.builder = std::mem::take(&mut flow.builder);
+            result?;
+            self.last_end = flow.pos;
+            self.skip_until = flow.pos;
+        }
+        Ok(())
+    }
+}
+
+/// TOML tables, arrays of tables and key/value pairs
+struct TomlParser<'a> {
+    text: &'a str,
+    bytes: &'a [u8],
+    pos: usize,
+    builder: Builder,
+    root: usize,
+    /// Node of each key under a table, by (table node, key)
+    children: HashMap<(usize, String), usize>,
+    /// Elements so far of each array of tables, by its node
+    table_arrays: HashMap<usize, Vec<usize>>,
+    /// Table the following key/value pairs belong to
+    table: usize,
+    /// Header node of that table, closed at the next header
+    section: Option<usize>,
+    last_end: usize,
+}
+
+impl<'a> TomlParser<'a> {
+    fn new(text: &'a str) -> Self {
+        let mut builder = Builder::default();
+        let root = builder.open(None, None, 0, 0);
+        Self {
+            text,
+            bytes: text.as_bytes(),
+            pos: 0,
+            builder,
+            root,
+            children: HashMap::new(),
+            table_arrays: HashMap::new(),
+            table: root,
+            section: None,
+            last_end: 0,
+        }
+    }
+
+    fn parse(mut self) -> Outline {
+        let error = self.statements().err();
+        if error.is_none() {
+            self.close_section();
+            self.builder.close(self.root, self.last_end);
+        }
+        self.builder.finish(self.text.len(), error)
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.bytes.get(self.pos).copied()
+    }
+
+    fn skip_spaces(&mut self) {
+        while matches!(self.peek(), Some(b' ' | b'\t')) {
+            self.pos += 1;
+        }
+    }
+
+    /// Skip whitespace, newlines and comments
+    fn skip_blank(&mut self) {
+        loop {
+            match self.peek() {
+                Some(b' ' | b'\t' | b'\r' | b'\n') => self.pos += 1,
+                Some(b'#') => {
+                    self.pos = match self.text[self.pos..].find('\n') {
+                        Some(newline) => self.pos + newline,
+                        None => self.bytes.len(),
+                    }
+                }
+                _ => return,
+            }
+        }
+    }
+
+    fn statements(&mut self) -> Parsed<()> {
+        loop {
+            self.skip_blank();
+            match self.peek() {
+                None => return Ok(()),
+                Some(b'[') => self.header()?,
+                Some(_) => {
+                    let table = self.table;
+                    self.key_value(table, 0)?;
+                }
+            }
+            self.last_end = self.pos;
+            // Only a comment may follow on the line
+            self.skip_spaces();
+            match self.peek() {
+                None | Some(b'\n' | b'\r' | b'#') => {}
+                _ => return Err(self.pos),
+            }
+        }
+    }
+
+    fn close_section(&mut self) {
+        if let Some(section) = self.section.take() {
+            self.builder.close(section, self.last_end);
+        }
+    }
+
+    /// `[table]` or `[[array.of.tables]]`
+    fn header(&mut self) -> Parsed<()> {
+        self.close_section();
+        let start = self.pos;
+        let array = self.text[self.pos..].starts_with("[[");
+        self.pos += if array { 2 } else { 1 };
+        self.skip_spaces();
+        let keys = self.dotted_key()?;
+        let close = if array { "]]" } else { "]" };
+        if !self.text[self.pos..].starts_with(close) {
+            return Err(self.pos);
+        }
+        self.pos += close.len();
+
+        let (last, parents) = keys.split_last().ok_or(start)?;
+        let mut table = self.root;
+        for (key, range) in parents {
+            table = self.child_table(table, key, range.clone());
+        }
+        let (key, range) = last;
+        let section = if array {
+            let table_array = match self.children.get(&(table, key.clone())) {
+                Some(&node) if self.table_arrays.contains_key(&node) => node,
+                _ => {
+                    let node = self.implicit(table, key, range.clone());
+                    self.table_arrays.insert(node, Vec::new());
+                    node
+                }
+            };
+            let index = self.table_arrays[&table_array].len();
+            let element = self.builder.open(
+                Some(table_array),
+                Some(PathSegment::Index(index)),
+                start,
+                self.pos,
+            );
+            self.table_arrays
+                .get_mut(&table_array)
+                .unwrap()
+                .push(element);
+            element
+        } else {
+            let node = self.builder.open(
+                Some(table),
+                Some(PathSegment::Key(key.clone())),
+                start,
+                self.pos,
+            );
+            self.children.insert((table, key.clone()), node);
+            node
+        };
+        self.table = section;
+        self.section = Some(section);
+        Ok(())
+    }
+
+    /// Node for `key` under `table`, the last element for an array of
+    /// tables, created if the document hasn't defined it yet
+    fn child_table(&mut self, table: usize, key: &str, range: Range<usize>) -> usize {
+        match self.children.get(&(table, key.to_string())) {
+            Some(&node) => match self.table_arrays.get(&node).and_then(|e| e.last()) {
+                Some(&element) => element,
+                None => node,
+            },
+            None => self.implicit(table, key, range),
+        }
+    }
+
+    /// A table only named as part of a longer key, spanning that name
+    fn implicit(&mut self, table: usize, key: &str, range: Range<usize>) -> usize {
+        let node = self.builder.open(
+            Some(table),
+            Some(PathSegment::Key(key.to_string())),
+            range.start,
+            range.start,
+        );
+        self.builder.close(node, range.end);
+        self.children.insert((table, key.to_string()), node);
+        node
+    }
+
+    /// `a.b = value` in `table`
+    fn key_value(&mut self, table: usize, depth: usize) -> Parsed<()> {
+        if depth > MAX_DEPTH {
+            return Err(self.pos);
+        }
+        let start = self.pos;
+        let keys = self.dotted_key()?;
+        if self.peek() != Some(b'=') {
+            return Err(self.pos);
+        }
+        self.pos += 1;
+        self.skip_spaces();
+
+        let (last, parents) = keys.split_last().ok_or(start)?;
+        let mut table = table;
+        for (key, range) in parents {
+            table = self.child_table(table, key, range.clone());
+        }
+        let node = self.builder.open(
+            Some(table),
+            Some(PathSegment::Key(last.0.clone())),
+            start,
+            self.pos,
+        );
+        self.children.insert((table, last.0.clone()), node);
+        self.value(node, depth)?;
+        self.builder.close(node, self.pos);
+        Ok(())
+    }
+
+    /// Keys separated by dots, with the range of each
+    fn dotted_key(&mut self) -> Parsed<Vec<(String, Range<usize>)>> {
+        let mut keys = Vec::new();
+        loop {
+            self.skip_spaces();
+            let start = self.pos;
+            let key = match self.peek() {
+                Some(b'"' | b'\'') => {
+                    let end = quoted_end(self.bytes, start, true).ok_or(start)?;
+                    self.pos = end;
+                    unquote(&self.text[start..end])
+                }
+                _ => {
+                    while matches!(self.peek(), Some(b) if b.is_ascii_alphanumeric() || b == b'_' || b == b'-')
+                    {
+                        self.pos += 1;
+                    }
+                    if self.pos == start {
+                        return Err(start);
+                    }
+                    self.text[start..self.pos].to_string()
+                }
+            };
+            keys.push((key, start..self.pos));
+            self.skip_spaces();
+            if self.peek() == Some(b'.') {
+                self.pos += 1;
+            } else {
+                return Ok(keys);
+            }
+        }
+    }
+
+    fn value(&mut self, node: usize, depth: usize) -> Parsed<()> {
+        match self.peek() {
+            Some(b'"' | b'\'') => {
+                let quote = self.bytes[self.pos];
+                let triple = [quote; 3];
+                if self.bytes[self.pos..].starts_with(&triple) {
+                    self.multiline_string(quote)
+                } else {
+                    self.pos = quoted_end(self.bytes, self.pos, true).ok_or(self.pos)?;
+                    Ok(())
+                }
+            }
+            Some(b'[') => {
+                self.pos += 1;
+                let mut index = 0;
+                loop {
+                    self.skip_blank();
+                    match self.peek() {
+                        Some(b']') => {
+                            self.pos += 1;
+                            return Ok(());
+                        }
+                        Some(_) => {
+                            let start = self.pos;
+                            let element = self.builder.open(
+                                Some(node),
+                                Some(PathSegment::Index(index)),
+                                start,
+                                start,
+                            );
+                            self.value(element, depth + 1)?;
+                            self.builder.close(element, self.pos);
+                            index += 1;
+                            self.skip_blank();
+                            match self.peek() {
+                                Some(b',') => self.pos += 1,
+                                Some(b']') => {}
+                                _ => return Err(self.pos),
+                            }
+                        }
+                        None => return Err(self.pos),
+                    }
+                }
+            }
+            Some(b'{') => {
+                self.pos += 1;
+                loop {
+                    self.skip_blank();
+                    match self.peek() {
+                        Some(b'}') => {
+                            self.pos += 1;
+                            return Ok(());
+                        }
+                        Some(_) => {
+                            self.key_value(node, depth + 1)?;
+                            self.skip_blank();
+                            match self.peek() {
+                                Some(b',') => self.pos += 1,
+                                Some(b'}') => {}
+                                _ => return Err(self.pos),
+                            }
+                        }
+                        None => return Err(self.pos),
+                    }
+                }
+            }
+            _ => {
+                // Numbers, booleans and dates (which may contain a space)
+                let start = self.pos;
+                let mut end = start;
+                while let Some(byte) = self.peek() {
+                    if matches!(byte, b',' | b']' | b'}' | b'#' | b'\n' | b'\r') {
+                        break;
+                    }
+                    self.pos += 1;
+                    if !matches!(byte, b' ' | b'\t') {
+                        end = self.pos;
+                    }
+                }
+                if end == start {
+                    return Err(start);
+                }
+                self.pos = end;
+                Ok(())
+            }
+        }
+    }
+
+    /// `"""…"""` or `'''…'''`, whose closing delimiter may be followed by
+    /// up to two more quotes that belong to the string
+    fn multiline_string(&mut self, quote: u8) -> Parsed<()> {
+        let start = self.pos;
+        let mut pos = start + 3;
+        while pos < self.bytes.len() {
+            let byte = self.bytes[pos];
+            if byte == b'\\' && quote == b'"' {
+                pos += 2;
+                continue;
+            }
+            if self.bytes[pos..].starts_with(&[quote; 3]) {
+                let mut end = pos + 3;
+                while end < self.bytes.len() && self.bytes[end] == quote && end < pos + 5 {
+                    end += 1;
+                }
+                self.pos = end;
+                return Ok(());
+            }
+            pos += 1;
+        }
+        Err(start)
+    }
+}
+
+/// Whether a key can be written without quotes in `style`
+fn is_bare_key(key: &str, style: DataPathStyle) -> bool {
+    let mut chars = key.chars();
+    let Some(first) = chars.next() else {
+        return false;
+    };
+    let rest_ok = |c: char| match style {
+        DataPathStyle::Dotted => c.is_alphanumeric() || c == '_' || c == '-' || c == '$',
+        DataPathStyle::Jq => c.is_ascii_alphanumeric() || c == '_',
+    };
+    (first.is_alphabetic() || first == '_' || (first == '$' && style == DataPathStyle::Dotted))
+        && chars.all(rest_ok)
+}
+
+/// Write a path as `a.b[3]["key with spaces"]` or, in jq style, as
+/// `.a.b[3]."key with spaces"`
+pub fn format_path(path: &[PathSegment], style: DataPathStyle) -> String {
+    let mut out = String::new();
+    for segment in path {
+        match segment {
+            PathSegment::Index(index) => out.push_str(&format!("[{}]", index)),
+            PathSegment::Key(key) => {
+                let quoted = serde_json::to_string(key).unwrap_or_default();
+                match style {
+                    DataPathStyle::Dotted if is_bare_key(key, style) => {
+                        if !out.is_empty() {
+                            out.push('.');
+                        }
+                        out.push_str(key);
+                    }
+                    DataPathStyle::Dotted => out.push_str(&format!("[{}]", quoted)),
+                    DataPathStyle::Jq if is_bare_key(key, style) => {
+                        out.push('.');
+                        out.push_str(key);
+                    }
+                    DataPathStyle::Jq => {
+                        out.push('.');
+                        out.push_str(&quoted);
+                    }
+                }
+            }
+        }
+    }
+    if out.is_empty() && style == DataPathStyle::Jq {
+        out.push('.');
+    }
+    out
+}
+
+/// Read a path written in either style of [`format_path`]
+///
+/// Returns None if it isn't a valid path.
+pub fn parse_path(text: &str) -> Option<Vec<PathSegment>> {
+    let text = text.trim();
+    let bytes = text.as_bytes();
+    let mut path = Vec::new();
+    let mut pos = 0;
+    while pos < bytes.len() {
+        match bytes[pos] {
+            b'.' => {
+                pos += 1;
+                match bytes.get(pos) {
+                    Some(b'"') => {
+                        let end = quoted_end(bytes, pos, true)?;
+                        path.push(PathSegment::Key(unquote(&text[pos..end])));
+                        pos = end;
+                    }
+                    // `.[0]` in jq, or a lone `.` for the root
+                    Some(b'[') | None => {}
+                    Some(_) => pos = bare_key(text, pos, &mut path)?,
+                }
+            }
+            b'[' => {
+                pos += 1;
+                if bytes.get(pos) == Some(&b'"') {
+                    let end = quoted_end(bytes, pos, true)?;
+                    path.push(PathSegment::Key(unquote(&text[pos..end])));
+                    pos = end;
+                } else {
+                    let digits = text[pos..].find(']')?;
+                    let index = text[pos..pos + digits].trim().parse().ok()?;
+                    path.push(PathSegment::Index(index));
+                    pos += digits;
+                }
+                if bytes.get(pos) != Some(&b']') {
+                    return None;
+                }
+                pos += 1;
+            }
+            _ if pos == 0 => pos = bare_key(text, pos, &mut path)?,
+            _ => return None,
+        }
+    }
+    Some(path)
+}
+
+/// Read an unquoted key starting at `pos`, returning where it ends
+fn bare_key(text: &str, pos: usize, path: &mut Vec<PathSegment>) -> Option<usize> {
+    let len = text[pos..]
+        .find(['.', '[', ']', '"'])
+        .unwrap_or(text.len() - pos);
+    if len == 0 {
+        return None;
+    }
+    path.push(PathSegment::Key(text[pos..pos + len].to_string()));
+    Some(pos + len)
+}
+
+/// Outline of a buffer, parsed again when its content changes
+#[derive(Debug, Default)]
+pub struct OutlineCache {
+    revision: u64,
+    format: Option<DataFormat>,
+    outline: Option<Outline>,
+}
+
+impl OutlineCache {
+    /// Outline of `buffer`; None unless it is a JSON, YAML or TOML file
+    /// small enough to parse
+    pub fn get(&mut self, buffer: &Buffer) -> Option<&Outline> {
+        let format = buffer.file_path().and_then(DataFormat::from_path)?;
+        if buffer.len() > MAX_OUTLINE_BYTES {
+            return None;
+        }
+        let stale = self.outline.is_none()
+            || self.revision != buffer.revision()
+            || self.format != Some(format);
+        if stale {
+            let text = buffer.to_string()?;
+            self.outline = Some(Outline::parse(format, &text));
+            self.revision = buffer.revision();
+            self.format = Some(format);
+        }
+        self.outline.as_ref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(key: &str) -> PathSegment {
+        PathSegment::Key(key.to_string())
+    }
+
+    /// Path at the first occurrence of `needle` in `text`
+    fn path_at(format: DataFormat, text: &str, needle: &str) -> String {
+        let offset = text.find(needle).expect("needle in text");
+        let outline = Outline::parse(format, text);
+        format_path(&outline.path_at(offset), DataPathStyle::Dotted)
+    }
+
+    #[test]
+    fn test_json_paths() {
+        let text = r#"{
+  "spec": {
+    "containers": [
+      {"name": "web", "env": [{"name": "A"}, {"name": "B", "value": "x"}]},
+      {"name": "db"}
+    ],
+    "odd key": true // comment
+  }
+}"#;
+        let json = DataFormat::Json;
+        assert_eq!(path_at(json, text, "\"web\""), "spec.containers[0].name");
+        assert_eq!(
+            path_at(json, text, "\"x\""),
+            "spec.containers[0].env[1].value"
+        );
+        assert_eq!(path_at(json, text, "\"db\""), "spec.containers[1].name");
+        assert_eq!(path_at(json, text, "true"), r#"spec["odd key"]"#);
+        assert_eq!(path_at(json, text, "{"), "");
+        assert!(Outline::parse(json, text).error().is_none());
+    }
+
+    #[test]
+    fn test_json_malformed_keeps_parsed_ancestors() {
+        let text = r#"{"a": {"b": [1, 2, oops here], "c": 1}}"#;
+        let outline = Outline::parse(DataFormat::Json, text);
+        assert!(outline.error().is_some());
+        // Past the last element that parsed the path stops at b
+        let offset = text.find("here").unwrap();
+        assert_eq!(
+            format_path(&outline.path_at(offset), DataPathStyle::Dotted),
+            "a.b"
+        );
+        assert_eq!(
+            format_path(
+                &outline.path_at(text.find("oops").unwrap()),
+                DataPathStyle::Dotted
+            ),
+            "a.b[2]"
+        );
+        assert_eq!(
+            format_path(
+                &outline.path_at(text.find("2,").unwrap()),
+                DataPathStyle::Dotted
+            ),
+            "a.b[1]"
+        );
+    }
+
+    #[test]
+    fn test_yaml_paths() {
+        let text = "\
+# deployment
+spec:
+  template:
+    containers:
+      - name: web
+        env:
+          - name: A
+          - name: B # comment
+            value: \"x\"
+      - name: db
+  replicas: 3
+list:
+- one
+- two: 2
+";
+        let yaml = DataFormat::Yaml;
+        assert_eq!(
+            path_at(yaml, text, "web"),
+            "spec.template.containers[0].name"
+        );
+        assert_eq!(
+            path_at(yaml, text, "\"x\""),
+            "spec.template.containers[0].env[1].value"
+        );
+        assert_eq!(
+            path_at(yaml, text, "db"),
+            "spec.template.containers[1].name"
+        );
+        assert_eq!(path_at(yaml, text, "3"), "spec.replicas");
+        assert_eq!(path_at(yaml, text, "one"), "list[0]");
+        assert_eq!(path_at(yaml, text, "2\n"), "list[1].two");
+        assert!(Outline::parse(yaml, text).error().is_none());
+    }
+
+    #[test]
+    fn test_yaml_anchors_aliases_and_flow() {
+        let text = "\
+defaults: &defaults
+  adapter: postgres
+  host: localhost
+development:
+  <<: *defaults
+  database: dev_db
+ports: [80, {name: https, port: 443}]
+script: |
+  echo \"a: b\"
+  # not a comment
+after: 1
+---
+second: doc
+";
+        let yaml = DataFormat::Yaml;
+        assert_eq!(path_at(yaml, text, "postgres"), "defaults.adapter");
+        assert_eq!(path_at(yaml, text, "*defaults"), "development[\"<<\"]");
+        assert_eq!(path_at(yaml, text, "dev_db"), "development.database");
+        assert_eq!(path_at(yaml, text, "80"), "ports[0]");
+        assert_eq!(path_at(yaml, text, "443"), "ports[1].port");
+        assert_eq!(path_at(yaml, text, "not a comment"), "script");
+        assert_eq!(path_at(yaml, text, "1\n"), "after");
+        assert_eq!(path_at(yaml, text, "doc"), "second");
+
+        let outline = Outline::parse(yaml, text);
+        // The anchor's value starts after the anchor
+        let value = outline
+            .value_range_around(text.find("defaults:").map(|o| o..o).unwrap())
+            .unwrap();
+        assert!(text[value].starts_with("adapter: postgres"));
+    }
+
+    #[test]
+    fn test_yaml_malformed_keeps_parsed_ancestors() {
+        let text = "a:\n  b:\n    c: 1\n    {broken\n  d: 2\n";
+        let outline = Outline::parse(DataFormat::Yaml, text);
+        assert!(outline.error().is_some());
+        let offset = text.find("broken").unwrap();
+        assert_eq!(
+            format_path(&outline.path_at(offset), DataPathStyle::Dotted),
+            "a.b"
+        );
+    }
+
+    #[test]
+    fn test_toml_tables_and_arrays_of_tables() {
+        let text = r#"name = "demo"
+server.host = "localhost"
+
+[package]
+version = "1.0" # comment
+authors = ["a", "b"]
+
+[dependencies.serde]
+features = ["derive"]
+inline = { x = 1, y.z = 2 }
+
+[[bin]]
+name = "first"
+
+[[bin]]
+name = "second"
+[bin.meta]
+tag = 'x'
+
+[multi]
+text = """
+line [not an array]
+"""
+when = 1979-05-27 07:32:00Z
+"#;
+        let toml = DataFormat::Toml;
+        assert_eq!(path_at(toml, text, "\"demo\""), "name");
+        assert_eq!(path_at(toml, text, "\"localhost\""), "server.host");
+        assert_eq!(path_at(toml, text, "\"1.0\""), "package.version");
+        assert_eq!(path_at(toml, text, "\"b\""), "package.authors[1]");
+        assert_eq!(
+            path_at(toml, text, "\"derive\""),
+            "dependencies.serde.features[0]"
+        );
+        assert_eq!(path_at(toml, text, "2 }"), "dependencies.serde.inline.y.z");
+        assert_eq!(path_at(toml, text, "\"first\""), "bin[0].name");
+        assert_eq!(path_at(toml, text, "\"second\""), "bin[1].name");
+        assert_eq!(path_at(toml, text, "'x'"), "bin[1].meta.tag");
+        assert_eq!(path_at(toml, text, "not an array"), "multi.text");
+        assert_eq!(path_at(toml, text, "07:32"), "multi.when");
+        assert!(Outline::parse(toml, text).error().is_none());
+
+        let outline = Outline::parse(toml, text);
+        assert_eq!(
+            outline.find(&[key("bin"), PathSegment::Index(1), key("name")]),
+            text.find("name = \"second\"")
+        );
+        assert_eq!(
+            outline.find(&[key("dependencies"), key("serde")]),
+            text.find("[dependencies.serde]")
+        );
+    }
+
+    #[test]
+    fn test_toml_malformed_keeps_parsed_ancestors() {
+        let text = "[a]\nb = [1, 2\nc = 3\n";
+        let outline = Outline::parse(DataFormat::Toml, text);
+        assert!(outline.error().is_some());
+        let offset = text.find("c = 3").unwrap();
+        assert_eq!(
+            format_path(&outline.path_at(offset), DataPathStyle::Dotted),
+            "a.b"
+        );
+    }
+
+    #[test]
+    fn test_select_value_walks_outwards() {
+        let text = r#"{"a": {"b": [1, 22]}}"#;
+        let outline = Outline::parse(DataFormat::Json, text);
+        let cursor = text.find("22").unwrap();
+        let step = |range: Range<usize>| outline.value_range_around(range).unwrap();
+
+        let value = step(cursor..cursor);
+        assert_eq!(&text[value.clone()], "22");
+        let value = step(value);
+        assert_eq!(&text[value.clone()], "[1, 22]");
+        let value = step(value);
+        assert_eq!(&text[value.clone()], r#"{"b": [1, 22]}"#);
+        let value = step(value);
+        assert_eq!(&text[value], text);
+
+        // On a key, its value
+        let on_key = text.find("\"b\"").unwrap();
+        assert_eq!(&text[step(on_key..on_key)], "[1, 22]");
+    }
+
+    #[test]
+    fn test_format_and_parse_paths() {
+        let path = vec![
+            key("spec"),
+            key("containers"),
+            PathSegment::Index(3),
+            key("odd key"),
+            key("app-name"),
+        ];
+        let dotted = format_path(&path, DataPathStyle::Dotted);
+        assert_eq!(dotted, r#"spec.containers[3]["odd key"].app-name"#);
+        let jq = format_path(&path, DataPathStyle::Jq);
+        assert_eq!(jq, r#".spec.containers[3]."odd key"."app-name""#);
+
+        assert_eq!(parse_path(&dotted), Some(path.clone()));
+        assert_eq!(parse_path(&jq), Some(path));
+        assert_eq!(parse_path("."), Some(Vec::new()));
+        assert_eq!(format_path(&[], DataPathStyle::Jq), ".");
+        assert_eq!(
+            parse_path(".items.[0]"),
+            Some(vec![key("items"), PathSegment::Index(0)])
+        );
+        assert_eq!(parse_path("a[x]"), None);
+        assert_eq!(parse_path("a]"), None);
+    }
+
+    #[test]
+    fn test_find_path() {
+        let text = "a:\n  b:\n    - x\n    - y\n";
+        let outline = Outline::parse(DataFormat::Yaml, text);
+        assert_eq!(
+            outline.find(&[key("a"), key("b"), PathSegment::Index(1)]),
+            text.find("- y")
+        );
+        assert_eq!(outline.find(&[key("a"), key("c")]), None);
+        assert_eq!(outline.find(&[]), Some(0));
+    }
+}
@@ -0,0 +1,211 @@
+//! Making untrusted text safe to draw
+//!
+//! Text from plugins, or from a file a plugin echoes, and file names, which
+//! on unix may hold any byte but `/` and NUL, can contain characters that
+//! terminals act on: ESC starts sequences that change the window title or
+//! move the cursor, CR and backspace move it too, and some terminals treat
+//! C1 characters (U+0080 to U+009F) the same way. Before such text reaches
+//! the view it goes through these functions, which draw each control
+//! character as a `<XX>` placeholder, as the buffer renderer does for
+//! control bytes in files, and cut text that is absurdly long.
+//!
+//! Strings coming out of the plugin runtime are valid UTF-8 already: V8
+//! strings are converted lossily, so a lone UTF-16 surrogate arrives as
+//! U+FFFD.
+
+use std::borrow::Cow;
+use std::fmt::Write;
+
+/// Longest status message, in characters
+pub const MAX_STATUS_CHARS: usize = 4096;
+/// Longest notification or popup message, in characters
+pub const MAX_MESSAGE_CHARS: usize = 4096;
+/// Longest line of a popup, virtual line or prompt input, in characters
+pub const MAX_LINE_CHARS: usize = 1024;
+/// Longest inline virtual text, in characters
+pub const MAX_INLINE_CHARS: usize = 256;
+/// Longest label: buffer and command names, titles, buttons, winbar
+/// segments, in characters
+pub const MAX_LABEL_CHARS: usize = 256;
+/// Longest gutter indicator symbol, in characters
+pub const MAX_SYMBOL_CHARS: usize = 8;
+
+/// Whether `c` is a C0 or C1 control character or DEL
+fn is_control(c: char) -> bool {
+    c.is_control()
+}
+
+/// Append the `<XX>` placeholder of a control character
+fn push_placeholder(out: &mut String, c: char) {
+    let _ = write!(out, "<{:02X}>", c as u32);
+}
+
+/// Cut `text` to `max_chars` characters, ending in `ellipsis` if anything
+/// was cut
+fn clamp<'a>(text: Cow<'a, str>, max_chars: usize, ellipsis: &str) -> Cow<'a, str> {
+    let Some((cut, _)) = text.char_indices().nth(max_chars) else {
+        return text;
+    };
+    let keep = max_chars.saturating_sub(ellipsis.chars().count());
+    let end = text.char_indices().nth(keep).map_or(cut, |(i, _)| i);
+    Cow::Owned(format!("{}{}", &text[..end], ellipsis))
+}
+
+/// Text for a single-line surface such as the status bar, a tab or virtual
+/// text
+///
+/// Line breaks and tabs become spaces (CRLF a single one), other control
+/// characters placeholders, and text longer than `max_chars` characters is
+/// cut, ending in `ellipsis`.
+pub fn sanitize_line<'a>(text: &'a str, max_chars: usize, ellipsis: &str) -> Cow<'a, str> {
+    let text = if text.chars().any(is_control) {
+        let mut out = String::with_capacity(text.len());
+        let mut chars = text.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '\r' => {
+                    chars.next_if_eq(&'\n');
+                    out.push(' ');
+                }
+                '\n' | '\t' => out.push(' '),
+                c if is_control(c) => push_placeholder(&mut out, c),
+                c => out.push(c),
+            }
+        }
+        Cow::Owned(out)
+    } else {
+        Cow::Borrowed(text)
+    };
+    clamp(text, max_chars, ellipsis)
+}
+
+/// Text for a surface that shows several lines, such as a notification
+///
+/// Line breaks are kept, as `\n`, and so are tabs; other control
+/// characters become placeholders and text longer than `max_chars`
+/// characters is cut, ending in `ellipsis`.
+pub fn sanitize_multiline<'a>(text: &'a str, max_chars: usize, ellipsis: &str) -> Cow<'a, str> {
+    let needs_change = text
+        .chars()
+        .any(|c| is_control(c) && c != '\n' && c != '\t');
+    let text = if needs_change {
+        let mut out = String::with_capacity(text.len());
+        let mut chars = text.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '\r' => {
+                    chars.next_if_eq(&'\n');
+                    out.push('\n');
+                }
+                '\n' | '\t' => out.push(c),
+                c if is_control(c) => push_placeholder(&mut out, c),
+                c => out.push(c),
+            }
+        }
+        Cow::Owned(out)
+    } else {
+        Cow::Borrowed(text)
+    };
+    clamp(text, max_chars, ellipsis)
+}
+
+/// Length of the SGR sequence (`ESC [ params m`, which only sets colors
+/// and attributes) at the start of `text`, if it starts with one
+fn sgr_len(text: &str) -> Option<usize> {
+    let params = text.strip_prefix("\x1b[")?;
+    let end = params.find(|c: char| !(c.is_ascii_digit() || c == ';' || c == ':'))?;
+    (params.as_bytes()[end] == b'm').then_some(2 + end + 1)
+}
+
+/// Text of a virtual buffer
+///
+/// Unlike the other surfaces, buffer text keeps its line breaks, tabs and
+/// CRs, which the buffer renderer draws itself, and its SGR color
+/// sequences, which buffers created with `parse_ansi` turn into styles.
+/// Any other escape sequence and C1 character becomes a placeholder, and
+/// the length is left alone.
+pub fn sanitize_buffer_text(text: &str) -> Cow<'_, str> {
+    let is_unsafe = |c: char| c == '\x1b' || ('\u{80}'..='\u{9f}').contains(&c);
+    if !text.contains(is_unsafe) {
+        return Cow::Borrowed(text);
+    }
+    let mut out = String::with_capacity(text.len());
+    let mut changed = false;
+    let mut rest = text;
+    while let Some(index) = rest.find(is_unsafe) {
+        out.push_str(&rest[..index]);
+        rest = &rest[index..];
+        if let Some(len) = sgr_len(rest) {
+            out.push_str(&rest[..len]);
+            rest = &rest[len..];
+        } else {
+            let c = rest.chars().next().unwrap_or_default();
+            push_placeholder(&mut out, c);
+            changed = true;
+            rest = &rest[c.len_utf8()..];
+        }
+    }
+    if !changed {
+        return Cow::Borrowed(text);
+    }
+    out.push_str(rest);
+    Cow::Owned(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clean_text_is_borrowed() {
+        assert!(matches!(
+            sanitize_line("plain text", 100, "…"),
+            Cow::Borrowed(_)
+        ));
+        assert!(matches!(
+            sanitize_multiline("two\nlines", 100, "…"),
+            Cow::Borrowed(_)
+        ));
+        assert!(matches!(
+            sanitize_buffer_text("\x1b[31mred\x1b[0m\r\n"),
+            Cow::Borrowed(_)
+        ));
+    }
+
+    #[test]
+    fn test_sanitize_line_replaces_controls() {
+        assert_eq!(
+            sanitize_line("\x1b]0;pwned\x07title", 100, "…"),
+            "<1B>]0;pwned<07>title"
+        );
+        assert_eq!(sanitize_line("a\r\nb\nc\td\re", 100, "…"), "a b c d e");
+        assert_eq!(sanitize_line("csi\u{9b}2J", 100, "…"), "csi<9B>2J");
+        assert_eq!(sanitize_line("del\u{7f}", 100, "…"), "del<7F>");
+    }
+
+    #[test]
+    fn test_sanitize_multiline_keeps_line_breaks() {
+        assert_eq!(
+            sanitize_multiline("one\r\ntwo\rthree\n\tfour\x08", 100, "…"),
+            "one\ntwo\nthree\n\tfour<08>"
+        );
+    }
+
+    #[test]
+    fn test_long_text_is_cut() {
+        assert_eq!(sanitize_line("abcdefgh", 5, "…"), "abcd…");
+        assert_eq!(sanitize_line("abcde", 5, "…"), "abcde");
+        assert_eq!(sanitize_line("äöüßéèê", 4, ".."), "äö..");
+        assert_eq!(sanitize_multiline("ab\ncdef", 4, "…"), "ab\n…");
+    }
+
+    #[test]
+    fn test_buffer_text_keeps_only_color_sequences() {
+        assert_eq!(
+            sanitize_buffer_text("\x1b[1;31mbold red\x1b[0m \x1b[2J\x1b]0;t\x07"),
+            "\x1b[1;31mbold red\x1b[0m <1B>[2J<1B>]0;t\x07"
+        );
+        assert_eq!(sanitize_buffer_text("end\x1b"), "end<1B>");
+        assert_eq!(sanitize_buffer_text("c1\u{90}"), "c1<90>");
+    }
+}
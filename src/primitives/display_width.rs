@@ -5,17 +5,57 @@
 //! cursor positioning, line wrapping, and UI layout with CJK characters,
 //! emoji, and other double-width or zero-width characters.
 
+use std::cell::Cell;
 use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
 
+thread_local! {
+    /// Render East Asian ambiguous-width characters as two cells.
+    /// Set from the `editor.ambiguous_width` config on the thread the editor
+    /// lays out and renders on, so editors on other threads (tests running in
+    /// parallel) keep their own setting.
+    static AMBIGUOUS_WIDE: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Set whether ambiguous-width characters are treated as wide (2 cells)
+/// on the current thread
+pub fn set_ambiguous_wide(wide: bool) {
+    AMBIGUOUS_WIDE.with(|flag| flag.set(wide));
+}
+
+/// Check whether ambiguous-width characters are treated as wide (2 cells)
+/// on the current thread
+#[inline]
+pub fn ambiguous_wide() -> bool {
+    AMBIGUOUS_WIDE.with(Cell::get)
+}
+
+/// Whether a character is East Asian "ambiguous": one cell in western
+/// terminals, two in most CJK ones
+#[inline]
+pub fn is_ambiguous_width(c: char) -> bool {
+    char_width_with(c, false) == 1 && char_width_with(c, true) == 2
+}
+
 /// Calculate the display width of a single character.
 ///
 /// Returns 0 for control characters and zero-width characters,
 /// 2 for CJK/fullwidth characters and emoji,
 /// 1 for most other characters.
+/// Ambiguous-width characters follow [`set_ambiguous_wide`].
 #[inline]
 pub fn char_width(c: char) -> usize {
+    char_width_with(c, ambiguous_wide())
+}
+
+/// Calculate the display width of a character with an explicit ambiguous-width mode.
+#[inline]
+pub fn char_width_with(c: char, ambiguous_wide: bool) -> usize {
     // unicode_width returns None for control characters
-    c.width().unwrap_or(0)
+    if ambiguous_wide {
+        c.width_cjk().unwrap_or(0)
+    } else {
+        c.width().unwrap_or(0)
+    }
 }
 
 /// Calculate the display width of a string.
@@ -24,7 +64,17 @@ pub fn char_width(c: char) -> usize {
 /// Use this instead of `.chars().count()` when calculating visual layout.
 #[inline]
 pub fn str_width(s: &str) -> usize {
-    s.width()
+    str_width_with(s, ambiguous_wide())
+}
+
+/// Calculate the display width of a string with an explicit ambiguous-width mode.
+#[inline]
+pub fn str_width_with(s: &str, ambiguous_wide: bool) -> usize {
+    if ambiguous_wide {
+        s.width_cjk()
+    } else {
+        s.width()
+    }
 }
 
 /// Extension trait for convenient width calculation on string types.
@@ -122,6 +172,32 @@ mod tests {
         assert_eq!(char_width('🚀'), 2);
     }
 
+    #[test]
+    fn test_ambiguous_width() {
+        // Box drawing and circled digits are East Asian "ambiguous"
+        assert_eq!(char_width_with('─', false), 1);
+        assert_eq!(char_width_with('─', true), 2);
+        assert_eq!(str_width_with("①②", false), 2);
+        assert_eq!(str_width_with("①②", true), 4);
+
+        // Unambiguous characters are unaffected
+        assert_eq!(str_width_with("a你", true), 3);
+
+        assert!(is_ambiguous_width('①'));
+        assert!(!is_ambiguous_width('a'));
+        assert!(!is_ambiguous_width('你'));
+    }
+
+    #[test]
+    fn test_ambiguous_wide_is_per_thread() {
+        set_ambiguous_wide(true);
+        assert_eq!(char_width('─'), 2);
+        let other = std::thread::spawn(|| char_width('─')).join().unwrap();
+        assert_eq!(other, 1);
+        set_ambiguous_wide(false);
+        assert_eq!(char_width('─'), 1);
+    }
+
     #[test]
     fn test_zero_width() {
         // Control characters
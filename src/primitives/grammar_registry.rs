@@ -29,12 +29,17 @@ pub struct GrammarRegistry {
 impl GrammarRegistry {
     /// Create a fully-loaded grammar registry for the editor
     /// Loads built-in, embedded, and user grammars
-    pub fn for_editor() -> Arc<Self> {
-        Arc::new(Self::load())
+    pub fn for_editor(grammars_dir: &Path) -> Arc<Self> {
+        Arc::new(Self::load_from(Some(grammars_dir)))
     }
 
     /// Load grammar registry, scanning user grammars directory
     pub fn load() -> Self {
+        Self::load_from(Self::grammars_directory().as_deref())
+    }
+
+    /// Load grammar registry, scanning `grammars_dir` for user grammars
+    pub fn load_from(grammars_dir: Option<&Path>) -> Self {
         let mut user_extensions = HashMap::new();
 
         // Start with syntect defaults, convert to builder to add more
@@ -45,9 +50,9 @@ impl GrammarRegistry {
         Self::add_embedded_grammars(&mut builder);
 
         // Add user grammars from config directory
-        if let Some(grammars_dir) = Self::grammars_directory() {
+        if let Some(grammars_dir) = grammars_dir {
             if grammars_dir.exists() {
-                Self::load_user_grammars_into(&grammars_dir, &mut builder, &mut user_extensions);
+                Self::load_user_grammars_into(grammars_dir, &mut builder, &mut user_extensions);
             }
         }
 
@@ -309,7 +314,7 @@ impl GrammarRegistry {
     /// will be respected for syntax highlighting.
     ///
     /// Checks in order:
-    /// 1. User-configured language filenames from config
+    /// 1. User-configured language filename patterns from config
     /// 2. User-configured language extensions from config
     /// 3. Falls back to `find_syntax_for_file` for built-in detection
     pub fn find_syntax_for_file_with_languages(
@@ -318,20 +323,18 @@ impl GrammarRegistry {
         languages: &std::collections::HashMap<String, crate::config::LanguageConfig>,
     ) -> Option<&SyntaxReference> {
         // Try filename match from languages config first
-        if let Some(filename) = path.file_name().and_then(|f| f.to_str()) {
-            for (_language_name, lang_config) in languages {
-                if lang_config.filenames.iter().any(|f| f == filename) {
-                    // Found a match - try to find syntax by grammar name
-                    if let Some(syntax) = self.find_syntax_by_name(&lang_config.grammar) {
-                        return Some(syntax);
-                    }
-                    // Also try finding by extension if grammar name didn't work
-                    // (some grammars are named differently)
-                    if !lang_config.extensions.is_empty() {
-                        if let Some(ext) = lang_config.extensions.first() {
-                            if let Some(syntax) = self.syntax_set.find_syntax_by_extension(ext) {
-                                return Some(syntax);
-                            }
+        for (_language_name, lang_config) in languages {
+            if lang_config.matches_filename(path) {
+                // Found a match - try to find syntax by grammar name
+                if let Some(syntax) = self.find_syntax_by_name(&lang_config.grammar) {
+                    return Some(syntax);
+                }
+                // Also try finding by extension if grammar name didn't work
+                // (some grammars are named differently)
+                if !lang_config.extensions.is_empty() {
+                    if let Some(ext) = lang_config.extensions.first() {
+                        if let Some(syntax) = self.syntax_set.find_syntax_by_extension(ext) {
+                            return Some(syntax);
                         }
                     }
                 }
@@ -567,6 +570,14 @@ mod tests {
                 formatter: None,
                 format_on_save: false,
                 on_save: vec![],
+                indent_rules: None,
+                on_type_formatting: false,
+                rulers: None,
+                auto_complete: true,
+                completion_trigger_characters: vec![],
+                save_actions: Vec::new(),
+                save_actions_on_failure: crate::config::SaveActionFailure::Continue,
+                save_actions_timeout_ms: 5000,
             },
         );
 
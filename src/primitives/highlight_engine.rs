@@ -19,6 +19,7 @@
 use crate::model::buffer::Buffer;
 use crate::primitives::grammar_registry::GrammarRegistry;
 use crate::primitives::highlighter::{HighlightCategory, HighlightSpan, Highlighter, Language};
+use crate::services::cache_budget::{BudgetedCache, CachePriority};
 use crate::view::theme::Theme;
 use std::ops::Range;
 use std::path::Path;
@@ -199,6 +200,9 @@ pub struct TextMateEngine {
     syntax_index: usize,
     cache: Option<TextMateCache>,
     last_buffer_len: usize,
+    /// Viewport requests answered from the cache and requests that parsed
+    cache_hits: u64,
+    cache_misses: u64,
     /// Tree-sitter language for non-highlighting features (indentation, semantic highlighting)
     /// Even when using syntect for highlighting, we track the language for other features
     ts_language: Option<Language>,
@@ -227,6 +231,8 @@ impl TextMateEngine {
             syntax_index,
             cache: None,
             last_buffer_len: 0,
+            cache_hits: 0,
+            cache_misses: 0,
             ts_language: None,
         }
     }
@@ -242,6 +248,8 @@ impl TextMateEngine {
             syntax_index,
             cache: None,
             last_buffer_len: 0,
+            cache_hits: 0,
+            cache_misses: 0,
             ts_language,
         }
     }
@@ -271,6 +279,7 @@ impl TextMateEngine {
                 && cache.range.end >= viewport_end
                 && self.last_buffer_len == buffer.len()
             {
+                self.cache_hits += 1;
                 return cache
                     .spans
                     .iter()
@@ -280,12 +289,14 @@ impl TextMateEngine {
                     .map(|span| HighlightSpan {
                         range: span.range.clone(),
                         color: span.category.color(theme),
+                        category: Some(span.category),
                     })
                     .collect();
             }
         }
 
         // Cache miss - parse viewport region
+        self.cache_misses += 1;
         let parse_start = viewport_start.saturating_sub(context_bytes);
         let parse_end = (viewport_end + context_bytes).min(buffer.len());
 
@@ -424,6 +435,7 @@ impl TextMateEngine {
             .map(|span| HighlightSpan {
                 range: span.range,
                 color: span.category.color(theme),
+                category: Some(span.category),
             })
             .collect()
     }
@@ -475,6 +487,18 @@ impl TextMateEngine {
         self.cache = None;
     }
 
+    /// Rough number of bytes of cached spans
+    pub fn cache_bytes(&self) -> usize {
+        self.cache.as_ref().map_or(0, |cache| {
+            cache.spans.capacity() * std::mem::size_of::<CachedSpan>()
+        })
+    }
+
+    /// Viewport requests answered from the cache and requests that parsed
+    pub fn cache_hits_and_misses(&self) -> (u64, u64) {
+        (self.cache_hits, self.cache_misses)
+    }
+
     /// Get syntax name
     pub fn syntax_name(&self) -> &str {
         &self.syntax_set.syntaxes()[self.syntax_index].name
@@ -711,6 +735,40 @@ impl HighlightEngine {
     }
 }
 
+/// The engine's viewport cache; dropping it makes the next render parse again
+impl BudgetedCache for HighlightEngine {
+    fn name(&self) -> &'static str {
+        "highlight"
+    }
+
+    fn priority(&self) -> CachePriority {
+        CachePriority::Normal
+    }
+
+    fn approx_bytes(&self) -> usize {
+        match self {
+            Self::TreeSitter(h) => h.cache_bytes(),
+            Self::TextMate(h) => h.cache_bytes(),
+            Self::None => 0,
+        }
+    }
+
+    fn hits_and_misses(&self) -> (u64, u64) {
+        match self {
+            Self::TreeSitter(h) => h.cache_hits_and_misses(),
+            Self::TextMate(h) => h.cache_hits_and_misses(),
+            Self::None => (0, 0),
+        }
+    }
+
+    fn shrink(&mut self, target_bytes: usize) {
+        // One cached range, kept or dropped as a whole
+        if self.approx_bytes() > target_bytes {
+            self.invalidate_all();
+        }
+    }
+}
+
 impl Default for HighlightEngine {
     fn default() -> Self {
         Self::None
@@ -814,6 +872,31 @@ mod tests {
         }
     }
 
+    /// Shrinking the engine drops its spans; the next render parses again and
+    /// gets the same highlighting
+    #[test]
+    fn test_shrunk_cache_highlights_again() {
+        let registry = GrammarRegistry::load();
+        let mut engine = HighlightEngine::for_file(Path::new("test.rs"), &registry);
+        let content = "fn main() {\n    let x = \"hello\";\n}\n";
+        let buffer = Buffer::from_str(content, 0);
+        let theme = Theme::default();
+
+        let first = engine.highlight_viewport(&buffer, 0, content.len(), &theme, 0);
+        engine.highlight_viewport(&buffer, 0, content.len(), &theme, 0);
+        assert!(engine.approx_bytes() > 0);
+        assert_eq!(engine.hits_and_misses(), (1, 1));
+
+        engine.shrink(0);
+        assert_eq!(engine.approx_bytes(), 0);
+        let again = engine.highlight_viewport(&buffer, 0, content.len(), &theme, 0);
+        assert_eq!(engine.hits_and_misses(), (1, 2));
+        assert_eq!(
+            again.iter().map(|s| s.range.clone()).collect::<Vec<_>>(),
+            first.iter().map(|s| s.range.clone()).collect::<Vec<_>>()
+        );
+    }
+
     /// Test that TextMateEngine produces correct byte offsets for CRLF content.
     /// This is a regression test for a bug where using str::lines() caused 1-byte
     /// offset drift per line because it strips line terminators.
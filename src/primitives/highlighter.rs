@@ -113,6 +113,8 @@ pub struct HighlightSpan {
     pub range: Range<usize>,
     /// Color for this span
     pub color: Color,
+    /// Syntax category, if the span comes from syntax highlighting
+    pub category: Option<HighlightCategory>,
 }
 
 /// Internal span used for caching (stores category instead of color)
@@ -160,7 +162,7 @@ impl Language {
             "c" | "h" => Some(Language::C),
             "cpp" | "hpp" | "cc" | "hh" | "cxx" | "hxx" => Some(Language::Cpp),
             "go" => Some(Language::Go),
-            "json" => Some(Language::Json),
+            "json" | "jsonc" => Some(Language::Json),
             "java" => Some(Language::Java),
             "cs" => Some(Language::CSharp),
             "php" => Some(Language::Php),
@@ -688,6 +690,9 @@ pub struct Highlighter {
     cache: Option<HighlightCache>,
     /// Last known buffer length (for detecting complete buffer changes)
     last_buffer_len: usize,
+    /// Viewport requests answered from the cache and requests that parsed
+    cache_hits: u64,
+    cache_misses: u64,
 }
 
 impl Highlighter {
@@ -700,6 +705,8 @@ impl Highlighter {
             config,
             cache: None,
             last_buffer_len: 0,
+            cache_hits: 0,
+            cache_misses: 0,
         })
     }
 
@@ -725,6 +732,7 @@ impl Highlighter {
                 && self.last_buffer_len == buffer.len()
             {
                 // Cache hit! Filter spans to the requested range and resolve colors from theme
+                self.cache_hits += 1;
                 return cache
                     .spans
                     .iter()
@@ -734,12 +742,14 @@ impl Highlighter {
                     .map(|span| HighlightSpan {
                         range: span.range.clone(),
                         color: span.category.color(theme),
+                        category: Some(span.category),
                     })
                     .collect();
             }
         }
 
         // Cache miss - need to parse
+        self.cache_misses += 1;
         // Extend range for context (helps with multi-line constructs like strings, comments, nested blocks)
         let parse_start = viewport_start.saturating_sub(context_bytes);
         let parse_end = (viewport_end + context_bytes).min(buffer.len());
@@ -819,6 +829,7 @@ impl Highlighter {
             .map(|span| HighlightSpan {
                 range: span.range,
                 color: span.category.color(theme),
+                category: Some(span.category),
             })
             .collect()
     }
@@ -840,6 +851,18 @@ impl Highlighter {
         self.cache = None;
     }
 
+    /// Rough number of bytes of cached spans
+    pub fn cache_bytes(&self) -> usize {
+        self.cache.as_ref().map_or(0, |cache| {
+            cache.spans.capacity() * std::mem::size_of::<CachedSpan>()
+        })
+    }
+
+    /// Viewport requests answered from the cache and requests that parsed
+    pub fn cache_hits_and_misses(&self) -> (u64, u64) {
+        (self.cache_hits, self.cache_misses)
+    }
+
     /// Get the current language
     pub fn language(&self) -> &Language {
         &self.language
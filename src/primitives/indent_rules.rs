@@ -0,0 +1,539 @@
+//! Rule-based auto-indentation
+//!
+//! Languages can configure simple regex rules ([`IndentRulesConfig`]) instead of
+//! relying on the tree-sitter + pattern matching approach in [`super::indent`]:
+//!
+//! - **Increase indent after**: when the text before the cursor matches, the new
+//!   line gets one more level (e.g. a line ending with `{` or `:`)
+//! - **Decrease indent when**: a line starting with a match (e.g. `}` or `else:`)
+//!   sits one level less. Checked for the text Enter moves onto the new line, and
+//!   when typing makes the current line match.
+//! - **Align continuation**: after an unclosed bracket followed by arguments,
+//!   continue under the first argument instead of indenting one level.
+//!
+//! Closing brackets are matched to the line that opened them, so `}` lines up with
+//! its block regardless of nesting. Other decreases are one level less than the
+//! previous non-blank line.
+//!
+//! All indents are widths in columns, with one level being `tab_size` columns;
+//! callers convert them to tabs or spaces.
+
+use crate::config::IndentRulesConfig;
+use crate::model::buffer::Buffer;
+use crate::primitives::display_width::str_width;
+use regex::Regex;
+
+/// Maximum bytes scanned before the cursor for reference lines and brackets
+const MAX_SCAN_BYTES: usize = 4000;
+
+/// Maximum bytes read after the cursor for the rest of its line
+const MAX_TAIL_BYTES: usize = 1000;
+
+/// Compiled indent rules for a language
+#[derive(Debug)]
+pub struct IndentRules {
+    increase_indent_after: Vec<Regex>,
+    decrease_indent_when: Vec<Regex>,
+    align_continuation: bool,
+}
+
+/// Indentation for the line created by Enter
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NewlineIndent {
+    /// Indent width of the new line
+    pub indent: usize,
+    /// Set when Enter splits an opening bracket from its closing one (`{|}`):
+    /// the closing text moves to a line of its own, with this indent
+    pub closing_indent: Option<usize>,
+}
+
+/// Text around the cursor
+struct LineContext {
+    /// Complete lines before the cursor's line, oldest first
+    lines: Vec<String>,
+    /// The cursor's line up to the cursor
+    head: String,
+    /// The cursor's line after the cursor
+    tail: String,
+}
+
+impl LineContext {
+    /// Read the lines before `position`, and the rest of the line from `tail_position`
+    fn read(buffer: &Buffer, position: usize, tail_position: usize) -> Self {
+        let scan_start = position.saturating_sub(MAX_SCAN_BYTES);
+        let before =
+            String::from_utf8_lossy(&buffer.slice_bytes(scan_start..position)).into_owned();
+        let mut lines: Vec<String> = before
+            .split('\n')
+            .map(|line| line.trim_end_matches('\r').to_string())
+            .collect();
+        let head = lines.pop().unwrap_or_default();
+        if scan_start > 0 && !lines.is_empty() {
+            // The first line was cut off by the scan window
+            lines.remove(0);
+        }
+
+        let tail_end = (tail_position + MAX_TAIL_BYTES).min(buffer.len());
+        let after = buffer.slice_bytes(tail_position.min(tail_end)..tail_end);
+        let tail = String::from_utf8_lossy(&after)
+            .split('\n')
+            .next()
+            .unwrap_or_default()
+            .trim_end_matches('\r')
+            .to_string();
+
+        Self { lines, head, tail }
+    }
+}
+
+impl IndentRules {
+    /// Compile the configured patterns
+    pub fn compile(config: &IndentRulesConfig) -> Result<Self, regex::Error> {
+        let compile = |patterns: &[String]| -> Result<Vec<Regex>, regex::Error> {
+            patterns.iter().map(|p| Regex::new(p)).collect()
+        };
+        Ok(Self {
+            increase_indent_after: compile(&config.increase_indent_after)?,
+            decrease_indent_when: compile(&config.decrease_indent_when)?,
+            align_continuation: config.align_continuation,
+        })
+    }
+
+    /// Whether a line opens a block (matches an "increase indent after" pattern)
+    fn increases(&self, line: &str) -> bool {
+        let text = line.trim();
+        !text.is_empty() && self.increase_indent_after.iter().any(|r| r.is_match(text))
+    }
+
+    /// Whether a line belongs one level left (matches a "decrease indent when" pattern)
+    fn decreases(&self, line: &str) -> bool {
+        let text = line.trim_start();
+        !text.is_empty() && self.decrease_indent_when.iter().any(|r| r.is_match(text))
+    }
+
+    /// Calculate the indent of the line created by pressing Enter at `position`.
+    ///
+    /// `tail_position` is where the text moved to the new line starts (the end of
+    /// the selection being replaced, or `position`).
+    pub fn newline_indent(
+        &self,
+        buffer: &Buffer,
+        position: usize,
+        tail_position: usize,
+        tab_size: usize,
+    ) -> NewlineIndent {
+        let ctx = LineContext::read(buffer, position, tail_position);
+        let tail = ctx.tail.trim_start();
+        let head_indent = indent_width(&ctx.head, tab_size);
+        let keep_head_indent = NewlineIndent {
+            indent: head_indent,
+            closing_indent: None,
+        };
+
+        // Enter inside the indentation of a line just pushes the line down
+        if ctx.head.trim().is_empty() && !tail.is_empty() {
+            return keep_head_indent;
+        }
+
+        // The line the new one follows: the text before the cursor, or the
+        // previous non-blank line when the cursor's line is blank
+        let (lines_before, reference) = if ctx.head.trim().is_empty() {
+            match ctx.lines.iter().rposition(|l| !l.trim().is_empty()) {
+                Some(i) => (&ctx.lines[..i], ctx.lines[i].as_str()),
+                None => return keep_head_indent,
+            }
+        } else {
+            (&ctx.lines[..], ctx.head.as_str())
+        };
+
+        let base = block_indent(lines_before, reference, tab_size);
+        let opened = self.increases(reference);
+        let indent = match self.continuation_column(reference, tab_size) {
+            Some(column) => column,
+            None if opened => base + tab_size,
+            None => base,
+        };
+
+        if !self.decreases(tail) {
+            return NewlineIndent {
+                indent,
+                closing_indent: None,
+            };
+        }
+
+        // The text moved down closes a block: line it up with the block's opener,
+        // on a line of its own if Enter was pressed right after the opener
+        let mut lines = ctx.lines.clone();
+        lines.push(ctx.head.clone());
+        let closing = decreased_indent(&lines, tail, tab_size)
+            .unwrap_or_else(|| indent.saturating_sub(tab_size));
+        let after_opener = ctx.head.trim_end().ends_with(is_opener);
+        if opened && after_opener && closing < indent {
+            NewlineIndent {
+                indent,
+                closing_indent: Some(closing),
+            }
+        } else {
+            NewlineIndent {
+                indent: closing,
+                closing_indent: None,
+            }
+        }
+    }
+
+    /// Calculate the indent for a closing bracket typed at `position` on a line
+    /// holding only whitespace.
+    ///
+    /// Returns None if the bracket doesn't match a "decrease indent when" pattern.
+    pub fn closing_indent(
+        &self,
+        buffer: &Buffer,
+        position: usize,
+        ch: char,
+        tab_size: usize,
+    ) -> Option<usize> {
+        let closer = ch.to_string();
+        if !self.decreases(&closer) {
+            return None;
+        }
+        let ctx = LineContext::read(buffer, position, position);
+        Some(self.dedent_target(&ctx.lines, &closer, tab_size))
+    }
+
+    /// Calculate the new indent of the line when typing `ch` at `position` makes
+    /// it match a "decrease indent when" pattern (e.g. the `:` of `else:`).
+    ///
+    /// Returns None if the line doesn't newly match, or is already far enough left.
+    pub fn dedent_on_type(
+        &self,
+        buffer: &Buffer,
+        position: usize,
+        ch: char,
+        tab_size: usize,
+    ) -> Option<usize> {
+        let ctx = LineContext::read(buffer, position, position);
+        let content = ctx.head.trim_start();
+        let typed = format!("{}{}{}", content, ch, ctx.tail);
+        if self.decreases(&format!("{}{}", content, ctx.tail)) || !self.decreases(&typed) {
+            return None;
+        }
+
+        let current = indent_width(&ctx.head, tab_size);
+        let target = self.dedent_target(&ctx.lines, &typed, tab_size);
+        (target < current).then_some(target)
+    }
+
+    /// Indent of a line with `content` that decreases, following `lines`
+    fn dedent_target(&self, lines: &[String], content: &str, tab_size: usize) -> usize {
+        if let Some(indent) = decreased_indent(lines, content, tab_size) {
+            return indent;
+        }
+        match lines.iter().rposition(|l| !l.trim().is_empty()) {
+            Some(i) => {
+                let base = block_indent(&lines[..i], &lines[i], tab_size);
+                if self.increases(&lines[i]) {
+                    base
+                } else {
+                    base.saturating_sub(tab_size)
+                }
+            }
+            None => 0,
+        }
+    }
+
+    /// With continuation alignment on, the column of the first argument after the
+    /// last unclosed bracket on `line` (None if there is no such argument)
+    fn continuation_column(&self, line: &str, tab_size: usize) -> Option<usize> {
+        if !self.align_continuation {
+            return None;
+        }
+        let open = unclosed_opener(line)?;
+        let rest = &line[open + 1..];
+        let argument = open + 1 + rest.find(|c: char| !c.is_whitespace())?;
+        let indent_end = line.len() - line.trim_start().len();
+        Some(indent_width(line, tab_size) + str_width(&line[indent_end..argument]))
+    }
+}
+
+/// Width of the leading whitespace of a line
+fn indent_width(line: &str, tab_size: usize) -> usize {
+    line.chars()
+        .take_while(|c| *c == ' ' || *c == '\t')
+        .map(|c| if c == '\t' { tab_size } else { 1 })
+        .sum()
+}
+
+fn is_opener(c: char) -> bool {
+    matches!(c, '(' | '[' | '{')
+}
+
+fn is_closer(c: char) -> bool {
+    matches!(c, ')' | ']' | '}')
+}
+
+/// Brackets on a line outside string literals, with their byte offsets
+fn brackets(line: &str) -> Vec<(usize, char)> {
+    let mut result = Vec::new();
+    let mut chars = line.char_indices().peekable();
+    let mut quote: Option<char> = None;
+    while let Some((i, c)) = chars.next() {
+        if let Some(q) = quote {
+            if c == '\\' {
+                chars.next();
+            } else if c == q {
+                quote = None;
+            }
+            continue;
+        }
+        match c {
+            '"' | '`' => quote = Some(c),
+            // Only a short char literal ('x', '\n'): a lone quote may be a
+            // lifetime or an apostrophe in a comment
+            '\'' => {
+                let rest = &line[i + 1..];
+                let literal_len = match rest.strip_prefix('\\') {
+                    Some(escaped) => escaped.chars().next().map(|c| 1 + c.len_utf8()),
+                    None => rest.chars().next().map(char::len_utf8),
+                };
+                if let Some(len) = literal_len {
+                    if rest[len..].starts_with('\'') {
+                        while chars.peek().is_some_and(|(j, _)| *j <= i + 1 + len) {
+                            chars.next();
+                        }
+                    }
+                }
+            }
+            c if is_opener(c) || is_closer(c) => result.push((i, c)),
+            _ => {}
+        }
+    }
+    result
+}
+
+/// Number of closing brackets on a line without an opener on the same line
+fn unmatched_closers(line: &str) -> usize {
+    let mut depth = 0usize;
+    let mut unmatched = 0;
+    for (_, c) in brackets(line) {
+        if is_opener(c) {
+            depth += 1;
+        } else if depth > 0 {
+            depth -= 1;
+        } else {
+            unmatched += 1;
+        }
+    }
+    unmatched
+}
+
+/// Byte offset of the last opening bracket on a line that it doesn't close
+fn unclosed_opener(line: &str) -> Option<usize> {
+    let mut open = Vec::new();
+    for (i, c) in brackets(line) {
+        if is_opener(c) {
+            open.push(i);
+        } else {
+            open.pop();
+        }
+    }
+    open.pop()
+}
+
+/// Index of the line opening the block that `needed` closing brackets after
+/// `lines` would close
+fn opener_line(lines: &[String], mut needed: usize) -> Option<usize> {
+    for (i, line) in lines.iter().enumerate().rev() {
+        for (_, c) in brackets(line).into_iter().rev() {
+            if is_closer(c) {
+                needed += 1;
+            } else {
+                needed -= 1;
+                if needed == 0 {
+                    return Some(i);
+                }
+            }
+        }
+    }
+    None
+}
+
+/// Indent of the block a line belongs to: a line closing brackets opened on
+/// earlier lines (`})`) belongs to the block of the line that opened them
+fn block_indent(lines_before: &[String], line: &str, tab_size: usize) -> usize {
+    match unmatched_closers(line) {
+        0 => indent_width(line, tab_size),
+        needed => opener_line(lines_before, needed)
+            .map(|i| indent_width(&lines_before[i], tab_size))
+            .unwrap_or_else(|| indent_width(line, tab_size)),
+    }
+}
+
+/// Indent of a line starting with a closing bracket: that of the line with the
+/// matching opener (None if `content` doesn't start with a closing bracket, or the
+/// opener isn't found)
+fn decreased_indent(lines: &[String], content: &str, tab_size: usize) -> Option<usize> {
+    if !content.trim_start().starts_with(is_closer) {
+        return None;
+    }
+    opener_line(lines, 1).map(|i| indent_width(&lines[i], tab_size))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn braces() -> IndentRules {
+        IndentRules::compile(&IndentRulesConfig {
+            increase_indent_after: vec![r#"\{[^}"'`]*$"#.to_string(), r#"\([^)"'`]*$"#.to_string()],
+            decrease_indent_when: vec![r"^[}\])]".to_string()],
+            align_continuation: false,
+        })
+        .unwrap()
+    }
+
+    fn python() -> IndentRules {
+        IndentRules::compile(&IndentRulesConfig {
+            increase_indent_after: vec![r":\s*(#.*)?$".to_string()],
+            decrease_indent_when: vec![
+                r"^(elif|else|except|finally)\b.*:\s*$".to_string(),
+                r"^[}\])]".to_string(),
+            ],
+            align_continuation: true,
+        })
+        .unwrap()
+    }
+
+    fn newline(rules: &IndentRules, text: &str) -> NewlineIndent {
+        let buffer = Buffer::from_str_test(text);
+        rules.newline_indent(&buffer, buffer.len(), buffer.len(), 4)
+    }
+
+    /// Enter at the `|` in `text`
+    fn newline_at_marker(rules: &IndentRules, text: &str) -> NewlineIndent {
+        let position = text.find('|').unwrap();
+        let buffer = Buffer::from_str_test(&text.replace('|', ""));
+        rules.newline_indent(&buffer, position, position, 4)
+    }
+
+    #[test]
+    fn test_invalid_pattern_is_an_error() {
+        let config = IndentRulesConfig {
+            increase_indent_after: vec!["(".to_string()],
+            ..Default::default()
+        };
+        assert!(IndentRules::compile(&config).is_err());
+    }
+
+    #[test]
+    fn test_increase_indent_after() {
+        assert_eq!(newline(&braces(), "fn main() {").indent, 4);
+        assert_eq!(newline(&braces(), "    if x {").indent, 8);
+        assert_eq!(newline(&braces(), "    foo(").indent, 8);
+        assert_eq!(newline(&python(), "def f():").indent, 4);
+        assert_eq!(newline(&python(), "if x:  # comment").indent, 4);
+
+        // No match: keep the indent
+        assert_eq!(newline(&braces(), "    let x = 1;").indent, 4);
+        assert_eq!(newline(&braces(), "    let s = \"{\";").indent, 4);
+    }
+
+    #[test]
+    fn test_blank_line_uses_previous_non_blank_line() {
+        assert_eq!(
+            newline(&braces(), "fn main() {\n    let x = 1;\n\n").indent,
+            4
+        );
+        assert_eq!(newline(&python(), "class A:\n\n").indent, 4);
+        assert_eq!(newline(&braces(), "").indent, 0);
+    }
+
+    #[test]
+    fn test_closing_line_returns_to_block_indent() {
+        let text = "fn main() {\n    if x {\n        y();\n    }";
+        assert_eq!(newline(&braces(), text).indent, 4);
+        let text = "fn main() {\n    foo(\n        a,\n    );";
+        assert_eq!(newline(&braces(), text).indent, 4);
+        assert_eq!(newline(&braces(), "struct A {\n    x: i32,\n}").indent, 0);
+    }
+
+    #[test]
+    fn test_decrease_indent_when_tail_closes_block() {
+        // Enter between braces puts the closing brace on its own line
+        assert_eq!(
+            newline_at_marker(&braces(), "    fn f() {|}"),
+            NewlineIndent {
+                indent: 8,
+                closing_indent: Some(4),
+            }
+        );
+        // Enter before a closing bracket lines it up with its opener
+        assert_eq!(
+            newline_at_marker(&braces(), "    foo(a, b|)").indent,
+            4,
+            "closing paren goes back to the opener's line indent"
+        );
+        // Enter in the indentation of a line keeps it
+        assert_eq!(
+            newline_at_marker(&braces(), "fn f() {\n  |  x();").indent,
+            2
+        );
+    }
+
+    #[test]
+    fn test_align_continuation() {
+        assert_eq!(newline(&python(), "    foo(a,").indent, 8);
+        assert_eq!(newline(&python(), "x = call(first, [1,").indent, 17);
+        // Bracket at the end of the line: nothing to align with
+        assert_eq!(newline(&python(), "foo(").indent, 0);
+        // Closed brackets don't count
+        assert_eq!(newline(&python(), "    foo(a)").indent, 4);
+        // Off for braces: indent one level
+        assert_eq!(newline(&braces(), "foo(a,").indent, 4);
+    }
+
+    #[test]
+    fn test_closing_indent_matches_opener() {
+        let rules = braces();
+        let buffer = Buffer::from_str_test("if a {\n    if b {\n        x\n    }\n    y\n    ");
+        assert_eq!(rules.closing_indent(&buffer, buffer.len(), '}', 4), Some(0));
+
+        let buffer = Buffer::from_str_test("if a {\n    if b {\n        x\n        ");
+        assert_eq!(rules.closing_indent(&buffer, buffer.len(), '}', 4), Some(4));
+
+        // Not a decrease pattern for these rules
+        let rules = IndentRules::compile(&IndentRulesConfig::default()).unwrap();
+        assert_eq!(rules.closing_indent(&buffer, buffer.len(), '}', 4), None);
+    }
+
+    #[test]
+    fn test_dedent_on_type() {
+        let rules = python();
+        let buffer = Buffer::from_str_test("if x:\n    a = 1\n    else");
+        assert_eq!(rules.dedent_on_type(&buffer, buffer.len(), ':', 4), Some(0));
+
+        // Not matching yet
+        assert_eq!(rules.dedent_on_type(&buffer, buffer.len(), ' ', 4), None);
+
+        // Already at the right level
+        let buffer = Buffer::from_str_test("if x:\n    if y:\n        a\n    else");
+        assert_eq!(rules.dedent_on_type(&buffer, buffer.len(), ':', 4), None);
+
+        // Matched before typing: don't dedent again
+        let buffer = Buffer::from_str_test("if x:\n    a\n    else:");
+        assert_eq!(rules.dedent_on_type(&buffer, buffer.len(), ' ', 4), None);
+    }
+
+    #[test]
+    fn test_brackets_skip_strings_and_char_literals() {
+        assert_eq!(unmatched_closers(r#"    "}" }"#), 1);
+        assert_eq!(unmatched_closers("    '}' }"), 1);
+        assert_eq!(unclosed_opener("fn f<'a>(x: &'a str) {"), Some(21));
+        assert_eq!(unclosed_opener(r#"print("(", x)"#), None);
+    }
+
+    #[test]
+    fn test_tabs_count_as_tab_size() {
+        assert_eq!(newline(&braces(), "\tif x {").indent, 8);
+        assert_eq!(indent_width("\t  x", 4), 6);
+    }
+}
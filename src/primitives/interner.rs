@@ -0,0 +1,86 @@
+//! Interned strings
+//!
+//! Names shared by many objects, like overlay and virtual text namespaces or
+//! theme color names, are stored once for the whole editor and referred to by
+//! a [`Symbol`], a small integer. Interned strings are never freed, so only
+//! names drawn from a small set should be interned, never per-object data.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{OnceLock, RwLock};
+
+/// An interned string
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Symbol(u32);
+
+#[derive(Default)]
+struct Interner {
+    symbols: HashMap<&'static str, Symbol>,
+    strings: Vec<&'static str>,
+}
+
+fn interner() -> &'static RwLock<Interner> {
+    static INTERNER: OnceLock<RwLock<Interner>> = OnceLock::new();
+    INTERNER.get_or_init(Default::default)
+}
+
+impl Symbol {
+    /// The symbol for `s`, interning it the first time
+    pub fn intern(s: &str) -> Self {
+        if let Some(symbol) = interner().read().unwrap().symbols.get(s) {
+            return *symbol;
+        }
+        let mut interner = interner().write().unwrap();
+        if let Some(symbol) = interner.symbols.get(s) {
+            return *symbol;
+        }
+        let symbol = Symbol(interner.strings.len() as u32);
+        let s: &'static str = Box::leak(s.to_string().into_boxed_str());
+        interner.strings.push(s);
+        interner.symbols.insert(s, symbol);
+        symbol
+    }
+
+    pub fn as_str(self) -> &'static str {
+        interner().read().unwrap().strings[self.0 as usize]
+    }
+
+    /// The symbol's number, for packing it into other data
+    pub(crate) fn index(self) -> u32 {
+        self.0
+    }
+
+    /// The symbol numbered `index`, if there is one
+    pub(crate) fn from_index(index: u32) -> Option<Self> {
+        let count = interner().read().unwrap().strings.len();
+        ((index as usize) < count).then_some(Symbol(index))
+    }
+}
+
+impl fmt::Debug for Symbol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+impl fmt::Display for Symbol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_intern_returns_same_symbol() {
+        let a = Symbol::intern("interner-test-a");
+        let b = Symbol::intern("interner-test-b");
+        assert_eq!(a, Symbol::intern(&"interner-test-a".to_string()));
+        assert_ne!(a, b);
+        assert_eq!(a.as_str(), "interner-test-a");
+        assert_eq!(Symbol::from_index(b.index()), Some(b));
+        assert_eq!(format!("{:?}", b), "\"interner-test-b\"");
+    }
+}
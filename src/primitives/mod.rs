@@ -5,16 +5,27 @@
 
 pub mod ansi;
 pub mod ansi_background;
+pub mod case_matching;
+pub mod column_align;
+pub mod data_path;
+pub mod display_text;
 pub mod display_width;
 pub mod grammar_registry;
 pub mod grapheme;
 pub mod highlight_engine;
 pub mod highlighter;
 pub mod indent;
+pub mod indent_rules;
+pub mod interner;
 pub mod line_iterator;
 pub mod line_wrapping;
+pub mod prose_navigation;
+pub mod relative_time;
+pub mod selection_stats;
 pub mod semantic_highlight;
 pub mod snippet;
 pub mod text_property;
+pub mod text_transform;
+pub mod truncate;
 pub mod visual_layout;
 pub mod word_navigation;
@@ -0,0 +1,774 @@
+//! Paragraph and sentence boundaries for moving through prose
+//!
+//! A paragraph is a block of non-blank lines. In markdown a heading line and
+//! each list item are paragraphs of their own as well, so moving by
+//! paragraph steps through a list one item at a time. Sentences never cross
+//! a paragraph: they end at `.`, `!`, `?`, an ellipsis or a CJK full stop,
+//! together with closing quotes and brackets after it, unless the period
+//! belongs to one of the configured abbreviations or the next word starts in
+//! lower case.
+//!
+//! Everything is found by reading lines outwards from the cursor, so the
+//! cost depends on the size of the paragraphs involved, not of the buffer.
+
+use crate::model::buffer::Buffer;
+use std::ops::Range;
+
+/// What decides the boundaries besides the text
+#[derive(Debug, Clone, Copy)]
+pub struct ProseOptions<'a> {
+    /// Apply the markdown rules for headings and list items
+    pub markdown: bool,
+    /// Words ending in a period that don't end a sentence, such as "e.g."
+    pub abbreviations: &'a [String],
+    pub estimated_line_length: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LineKind {
+    Blank,
+    Text,
+    /// Markdown heading or list item, with the length of its marker
+    Marker(usize),
+}
+
+/// A buffer line with its line ending
+#[derive(Debug, Clone)]
+struct Line {
+    start: usize,
+    text: String,
+    kind: LineKind,
+}
+
+impl Line {
+    fn new(start: usize, text: String, markdown: bool) -> Self {
+        let kind = if text.trim().is_empty() {
+            LineKind::Blank
+        } else {
+            match markdown.then(|| markdown_marker_len(&text)).flatten() {
+                Some(len) => LineKind::Marker(len),
+                None => LineKind::Text,
+            }
+        };
+        Self { start, text, kind }
+    }
+
+    /// The empty line after a buffer's final line ending
+    fn end_of_buffer(len: usize) -> Self {
+        Self {
+            start: len,
+            text: String::new(),
+            kind: LineKind::Blank,
+        }
+    }
+
+    fn first_non_blank(&self) -> usize {
+        self.start + (self.text.len() - self.text.trim_start().len())
+    }
+
+    /// Where the text after the heading or list marker starts
+    fn body_start(&self) -> usize {
+        match self.kind {
+            LineKind::Marker(len) => self.start + len,
+            _ => self.first_non_blank(),
+        }
+    }
+
+    /// End of the line's last non-blank character
+    fn text_end(&self) -> usize {
+        self.start + self.text.trim_end().len()
+    }
+}
+
+/// Bytes taken by the indentation, marker and following space of a
+/// markdown heading or list item
+fn markdown_marker_len(line: &str) -> Option<usize> {
+    let trimmed = line.trim_start();
+    let indent = line.len() - trimmed.len();
+    let bytes = trimmed.as_bytes();
+
+    let marker = match bytes.first()? {
+        b'#' => {
+            let hashes = bytes.iter().take_while(|&&b| b == b'#').count();
+            (hashes <= 6).then_some(hashes)?
+        }
+        b'-' | b'*' | b'+' => 1,
+        b'0'..=b'9' => {
+            let digits = bytes.iter().take_while(|b| b.is_ascii_digit()).count();
+            match bytes.get(digits) {
+                Some(b'.') | Some(b')') if digits <= 9 => digits + 1,
+                _ => return None,
+            }
+        }
+        _ => return None,
+    };
+    // The marker has to be followed by a space, or end a heading line
+    let rest = &trimmed[marker..];
+    let spaces = rest.len() - rest.trim_start_matches([' ', '\t']).len();
+    if spaces == 0 && !(bytes[0] == b'#' && rest.trim().is_empty()) {
+        return None;
+    }
+    let mut len = marker + spaces;
+    // Task list items: "- [ ] " and "- [x] "
+    let rest = &trimmed[len..];
+    if bytes[0] != b'#' && ["[ ] ", "[x] ", "[X] "].iter().any(|t| rest.starts_with(t)) {
+        len += 4;
+    }
+    Some(indent + len)
+}
+
+/// Whether a paragraph starts at `line`, given the line before it
+fn starts_paragraph(prev: Option<&Line>, line: &Line) -> bool {
+    match line.kind {
+        LineKind::Blank => false,
+        LineKind::Marker(_) => true,
+        LineKind::Text => match prev {
+            None => true,
+            // Text after a heading starts a paragraph, text after a list
+            // item continues it
+            Some(prev) => {
+                prev.kind == LineKind::Blank
+                    || matches!(prev.kind, LineKind::Marker(_)) && is_heading(prev)
+            }
+        },
+    }
+}
+
+fn is_heading(line: &Line) -> bool {
+    line.text.trim_start().starts_with('#')
+}
+
+/// A paragraph of the buffer
+#[derive(Debug, Clone)]
+struct Paragraph {
+    /// From the first non-blank character to the end of the last one
+    range: Range<usize>,
+    /// Text from the body start (after a markdown marker) to the end
+    body_start: usize,
+    body: String,
+}
+
+impl Paragraph {
+    fn from_lines(lines: &[Line]) -> Self {
+        let first = &lines[0];
+        let last = &lines[lines.len() - 1];
+        let body_start = first.body_start();
+        let end = last.text_end().max(body_start);
+        let mut body = String::new();
+        for line in lines {
+            let from = body_start.saturating_sub(line.start).min(line.text.len());
+            body.push_str(line.text.get(from..).unwrap_or(""));
+        }
+        body.truncate(end - body_start);
+        Self {
+            range: first.first_non_blank()..end,
+            body_start,
+            body,
+        }
+    }
+
+    /// Sentences of the paragraph as buffer ranges
+    fn sentences(&self, abbreviations: &[String]) -> Vec<Range<usize>> {
+        sentence_spans(&self.body, abbreviations)
+            .into_iter()
+            .map(|span| span.start + self.body_start..span.end + self.body_start)
+            .collect()
+    }
+}
+
+/// The line containing `pos`
+fn line_at(buffer: &mut Buffer, pos: usize, options: &ProseOptions) -> Line {
+    let mut iter = buffer.line_iterator(pos, options.estimated_line_length);
+    match iter.next() {
+        Some((start, text)) => Line::new(start, text, options.markdown),
+        None => Line::end_of_buffer(buffer.len()),
+    }
+}
+
+/// The line before the one starting at `line_start`
+fn line_before(buffer: &mut Buffer, line_start: usize, options: &ProseOptions) -> Option<Line> {
+    let mut iter = buffer.line_iterator(line_start, options.estimated_line_length);
+    iter.prev()
+        .map(|(start, text)| Line::new(start, text, options.markdown))
+}
+
+/// The paragraph containing `pos`; none on a blank line
+fn paragraph_at(buffer: &mut Buffer, pos: usize, options: &ProseOptions) -> Option<Paragraph> {
+    let line = line_at(buffer, pos, options);
+    if line.kind == LineKind::Blank {
+        return None;
+    }
+
+    // Last line first while reading upwards
+    let mut lines = vec![line];
+    let mut iter = buffer.line_iterator(lines[0].start, options.estimated_line_length);
+    loop {
+        let prev = iter
+            .prev()
+            .map(|(start, text)| Line::new(start, text, options.markdown));
+        if starts_paragraph(prev.as_ref(), &lines[lines.len() - 1]) {
+            break;
+        }
+        // A line that doesn't start a paragraph always has one before it
+        lines.push(prev?);
+    }
+    lines.reverse();
+
+    let last = &lines[lines.len() - 1];
+    let after = last.start + last.text.len();
+    if last.text.ends_with('\n') && after < buffer.len() {
+        let mut iter = buffer.line_iterator(after, options.estimated_line_length);
+        while let Some((start, text)) = iter.next() {
+            let next = Line::new(start, text, options.markdown);
+            if next.kind == LineKind::Blank || starts_paragraph(lines.last(), &next) {
+                break;
+            }
+            lines.push(next);
+        }
+    }
+    Some(Paragraph::from_lines(&lines))
+}
+
+/// First non-blank character of the next paragraph after `pos`
+fn next_paragraph_start(buffer: &mut Buffer, pos: usize, options: &ProseOptions) -> Option<usize> {
+    let current = line_at(buffer, pos, options);
+    if pos < current.first_non_blank() {
+        let prev = line_before(buffer, current.start, options);
+        if starts_paragraph(prev.as_ref(), &current) {
+            return Some(current.first_non_blank());
+        }
+    }
+
+    let after = current.start + current.text.len();
+    if after >= buffer.len() || !current.text.ends_with('\n') {
+        return None;
+    }
+    let mut iter = buffer.line_iterator(after, options.estimated_line_length);
+    let mut prev = current;
+    while let Some((start, text)) = iter.next() {
+        let line = Line::new(start, text, options.markdown);
+        if starts_paragraph(Some(&prev), &line) {
+            return Some(line.first_non_blank());
+        }
+        prev = line;
+    }
+    None
+}
+
+/// First non-blank character of the nearest paragraph starting before `pos`
+fn prev_paragraph_start(buffer: &mut Buffer, pos: usize, options: &ProseOptions) -> Option<usize> {
+    let mut line = line_at(buffer, pos, options);
+    let mut iter = buffer.line_iterator(line.start, options.estimated_line_length);
+    loop {
+        let prev = iter
+            .prev()
+            .map(|(start, text)| Line::new(start, text, options.markdown));
+        if starts_paragraph(prev.as_ref(), &line) && line.first_non_blank() < pos {
+            return Some(line.first_non_blank());
+        }
+        line = prev?;
+    }
+}
+
+/// Start of the next paragraph, or the end of the buffer
+pub fn paragraph_forward(buffer: &mut Buffer, pos: usize, options: &ProseOptions) -> usize {
+    next_paragraph_start(buffer, pos, options).unwrap_or(buffer.len())
+}
+
+/// Start of the paragraph the cursor is in, or of the one before when
+/// already there
+pub fn paragraph_backward(buffer: &mut Buffer, pos: usize, options: &ProseOptions) -> usize {
+    prev_paragraph_start(buffer, pos, options).unwrap_or(0)
+}
+
+/// Start of the next sentence, or the end of the buffer
+pub fn sentence_forward(buffer: &mut Buffer, pos: usize, options: &ProseOptions) -> usize {
+    if let Some(paragraph) = paragraph_at(buffer, pos, options) {
+        let next = paragraph
+            .sentences(options.abbreviations)
+            .into_iter()
+            .find(|sentence| sentence.start > pos);
+        if let Some(sentence) = next {
+            return sentence.start;
+        }
+    }
+    match next_paragraph_start(buffer, pos, options) {
+        Some(start) => paragraph_at(buffer, start, options)
+            .and_then(|p| p.sentences(options.abbreviations).first().cloned())
+            .map_or(start, |sentence| sentence.start),
+        None => buffer.len(),
+    }
+}
+
+/// Start of the sentence the cursor is in, or of the one before when
+/// already there
+pub fn sentence_backward(buffer: &mut Buffer, pos: usize, options: &ProseOptions) -> usize {
+    let current = match paragraph_at(buffer, pos, options) {
+        Some(paragraph) => Some(paragraph),
+        // On a blank line, the paragraph above
+        None => prev_paragraph_start(buffer, pos, options)
+            .and_then(|start| paragraph_at(buffer, start, options)),
+    };
+    let Some(paragraph) = current else {
+        return 0;
+    };
+    let before = paragraph
+        .sentences(options.abbreviations)
+        .into_iter()
+        .rev()
+        .find(|sentence| sentence.start < pos);
+    if let Some(sentence) = before {
+        return sentence.start;
+    }
+    match prev_paragraph_start(buffer, paragraph.range.start, options) {
+        Some(start) => paragraph_at(buffer, start, options)
+            .and_then(|p| p.sentences(options.abbreviations).last().cloned())
+            .map_or(start, |sentence| sentence.start),
+        None => 0,
+    }
+}
+
+/// The sentence at `pos` with the blanks that follow it up to the next
+/// sentence, or, for the last sentence of a paragraph, the blanks before it
+///
+/// Between two sentences the following one is taken. None on a blank line.
+pub fn sentence_range(
+    buffer: &mut Buffer,
+    pos: usize,
+    options: &ProseOptions,
+) -> Option<Range<usize>> {
+    let paragraph = paragraph_at(buffer, pos, options)?;
+    let sentences = paragraph.sentences(options.abbreviations);
+    let index = sentences
+        .iter()
+        .position(|sentence| pos < sentence.end)
+        .unwrap_or(sentences.len().checked_sub(1)?);
+    let sentence = &sentences[index];
+    Some(match (sentences.get(index + 1), index.checked_sub(1)) {
+        (Some(next), _) => sentence.start..next.start,
+        (None, Some(prev)) => sentences[prev].end..sentence.end,
+        (None, None) => sentence.clone(),
+    })
+}
+
+/// The text to delete from `pos` to the end of its paragraph, or to the
+/// next paragraph when already at the end, removing the lines between
+pub fn paragraph_delete_forward(
+    buffer: &mut Buffer,
+    pos: usize,
+    options: &ProseOptions,
+) -> Range<usize> {
+    match paragraph_at(buffer, pos, options) {
+        Some(paragraph) if pos < paragraph.range.end => pos..paragraph.range.end,
+        _ => pos..paragraph_forward(buffer, pos, options),
+    }
+}
+
+/// The text to delete from the start of the paragraph to `pos`, or from the
+/// end of the previous paragraph when already at the start
+pub fn paragraph_delete_backward(
+    buffer: &mut Buffer,
+    pos: usize,
+    options: &ProseOptions,
+) -> Range<usize> {
+    match paragraph_at(buffer, pos, options) {
+        Some(paragraph) if pos > paragraph.range.start => paragraph.range.start..pos,
+        _ => previous_paragraph_end(buffer, pos, options)..pos,
+    }
+}
+
+/// The text to delete from `pos` to the next sentence of its paragraph, to
+/// the end of the paragraph after its last sentence, or to the next
+/// paragraph when already at the end
+pub fn sentence_delete_forward(
+    buffer: &mut Buffer,
+    pos: usize,
+    options: &ProseOptions,
+) -> Range<usize> {
+    match paragraph_at(buffer, pos, options) {
+        Some(paragraph) if pos < paragraph.range.end => {
+            let end = paragraph
+                .sentences(options.abbreviations)
+                .into_iter()
+                .find(|sentence| sentence.start > pos)
+                .map_or(paragraph.range.end, |sentence| sentence.start);
+            pos..end
+        }
+        _ => pos..paragraph_forward(buffer, pos, options),
+    }
+}
+
+/// The text to delete from the start of the sentence to `pos` (or of the
+/// sentence before, when already at a start), or from the end of the
+/// previous paragraph when at the start of a paragraph
+pub fn sentence_delete_backward(
+    buffer: &mut Buffer,
+    pos: usize,
+    options: &ProseOptions,
+) -> Range<usize> {
+    match paragraph_at(buffer, pos, options) {
+        Some(paragraph) if pos > paragraph.body_start => {
+            let start = paragraph
+                .sentences(options.abbreviations)
+                .into_iter()
+                .rev()
+                .find(|sentence| sentence.start < pos)
+                .map_or(paragraph.body_start, |sentence| sentence.start);
+            start..pos
+        }
+        Some(paragraph) if pos > paragraph.range.start => paragraph.range.start..pos,
+        _ => previous_paragraph_end(buffer, pos, options)..pos,
+    }
+}
+
+/// End of the last paragraph before `pos`, or the start of the buffer
+fn previous_paragraph_end(buffer: &mut Buffer, pos: usize, options: &ProseOptions) -> usize {
+    prev_paragraph_start(buffer, pos, options)
+        .and_then(|start| paragraph_at(buffer, start, options))
+        .map_or(0, |paragraph| paragraph.range.end.min(pos))
+}
+
+/// Whether a character ends a sentence
+fn is_terminator(ch: char) -> bool {
+    matches!(ch, '.' | '!' | '?' | '…' | '‼' | '⁇' | '⁈' | '⁉') || is_cjk_terminator(ch)
+}
+
+/// Full stops of CJK text, which end a sentence without a space after them
+fn is_cjk_terminator(ch: char) -> bool {
+    matches!(ch, '。' | '！' | '？' | '．' | '｡')
+}
+
+/// Closing quotes and brackets that belong to the sentence they follow
+fn is_closer(ch: char) -> bool {
+    matches!(
+        ch,
+        '"' | '\'' | '”' | '’' | '»' | '›' | ')' | ']' | '）' | '」' | '』' | '】' | '〉' | '》'
+    )
+}
+
+/// Whether the word ending at `end` (a period) is one of the abbreviations
+fn ends_with_abbreviation(text: &str, end: usize, abbreviations: &[String]) -> bool {
+    let word_start = text[..end]
+        .rfind(|c: char| c.is_whitespace() || matches!(c, '(' | '[' | '"' | '“' | '‘'))
+        .map_or(0, |i| {
+            i + text[i..].chars().next().map_or(1, char::len_utf8)
+        });
+    let word = &text[word_start..=end];
+    abbreviations
+        .iter()
+        .any(|abbreviation| abbreviation.eq_ignore_ascii_case(word) || abbreviation == word)
+}
+
+/// Sentences of a paragraph's text, as byte ranges without the blanks
+/// between them
+///
+/// A sentence ends after a run of terminators and the closing quotes and
+/// brackets after it, when followed by a blank or the end of the text. A
+/// CJK full stop ends it even without a blank. A run of periods doesn't end
+/// it when the next word starts in lower case, nor does any run followed by
+/// a closing quote, and a single period doesn't when it ends one of
+/// `abbreviations`.
+pub fn sentence_spans(text: &str, abbreviations: &[String]) -> Vec<Range<usize>> {
+    let chars: Vec<(usize, char)> = text.char_indices().collect();
+    let mut spans = Vec::new();
+    let mut start = None;
+    let mut i = 0;
+    while i < chars.len() {
+        let (offset, ch) = chars[i];
+        if start.is_none() {
+            if ch.is_whitespace() {
+                i += 1;
+                continue;
+            }
+            start = Some(offset);
+        }
+        if !is_terminator(ch) {
+            i += 1;
+            continue;
+        }
+
+        let mut j = i;
+        while j < chars.len() && is_terminator(chars[j].1) {
+            j += 1;
+        }
+        let run: Vec<char> = chars[i..j].iter().map(|&(_, c)| c).collect();
+        let run_end = j;
+        while j < chars.len() && is_closer(chars[j].1) {
+            j += 1;
+        }
+        let quoted = j > run_end;
+        let end = chars.get(j).map_or(text.len(), |&(offset, _)| offset);
+
+        let cjk = run.iter().any(|&c| is_cjk_terminator(c));
+        let followed_by_blank = chars.get(j).is_none_or(|&(_, c)| c.is_whitespace());
+        let periods_only = run.iter().all(|&c| c == '.' || c == '…');
+        let next_word_lowercase = chars[j..]
+            .iter()
+            .find(|&&(_, c)| !c.is_whitespace())
+            .is_some_and(|&(_, c)| c.is_lowercase());
+        let abbreviation = run == ['.'] && ends_with_abbreviation(text, offset, abbreviations);
+
+        // "Why?" she asked: a quoted question or exclamation goes on too
+        let continues = next_word_lowercase && (periods_only || quoted) || abbreviation;
+        let ends = cjk || followed_by_blank && !continues;
+        if ends {
+            spans.push(start.take().unwrap_or(offset)..end);
+        }
+        i = j;
+    }
+    if let Some(start) = start {
+        spans.push(start..text.trim_end().len().max(start));
+    }
+    spans
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn abbreviations() -> Vec<String> {
+        ["e.g.", "i.e.", "Dr.", "vs."]
+            .iter()
+            .map(|s| s.to_string())
+            .collect()
+    }
+
+    fn sentences(text: &str) -> Vec<&str> {
+        sentence_spans(text, &abbreviations())
+            .into_iter()
+            .map(|span| &text[span])
+            .collect()
+    }
+
+    fn options(abbreviations: &[String], markdown: bool) -> ProseOptions<'_> {
+        ProseOptions {
+            markdown,
+            abbreviations,
+            estimated_line_length: 80,
+        }
+    }
+
+    #[test]
+    fn test_sentences_end_at_terminators() {
+        assert_eq!(
+            sentences("One here. Two there! Three? Four"),
+            ["One here.", "Two there!", "Three?", "Four"]
+        );
+        assert_eq!(sentences("  Lead in.  \n Next.\n"), ["Lead in.", "Next."]);
+        // No blank after the period: a number or a file name
+        assert_eq!(
+            sentences("Pi is 3.14 roughly. See a.txt now."),
+            ["Pi is 3.14 roughly.", "See a.txt now."]
+        );
+    }
+
+    #[test]
+    fn test_abbreviations_do_not_end_sentences() {
+        assert_eq!(
+            sentences("Use a tool, e.g. Fresh. Ask Dr. Smith vs. Jones. Done"),
+            [
+                "Use a tool, e.g. Fresh.",
+                "Ask Dr. Smith vs. Jones.",
+                "Done"
+            ]
+        );
+        // Ignoring case, and after an opening bracket
+        assert_eq!(
+            sentences("Some (E.G. This) stay. Yes"),
+            ["Some (E.G. This) stay.", "Yes"]
+        );
+        // Words merely ending like one do end sentences
+        assert_eq!(sentences("Meet the staff. Go"), ["Meet the staff.", "Go"]);
+    }
+
+    #[test]
+    fn test_ellipses() {
+        assert_eq!(
+            sentences("Wait... what now? Well… Then go."),
+            ["Wait... what now?", "Well…", "Then go."]
+        );
+        assert_eq!(sentences("And so..."), ["And so..."]);
+    }
+
+    #[test]
+    fn test_closing_quotes_belong_to_the_sentence() {
+        assert_eq!(
+            sentences("He said \"Stop.\" Then left. (It rained.) ‘Fine.’ Ok"),
+            [
+                "He said \"Stop.\"",
+                "Then left.",
+                "(It rained.)",
+                "‘Fine.’",
+                "Ok"
+            ]
+        );
+        // A lower case word after a quoted question continues the sentence
+        assert_eq!(
+            sentences("\"Why?\" she asked. No"),
+            ["\"Why?\" she asked.", "No"]
+        );
+    }
+
+    #[test]
+    fn test_cjk_sentence_endings() {
+        assert_eq!(
+            sentences("今日は晴れです。明日は雨！本当？「はい。」次"),
+            [
+                "今日は晴れです。",
+                "明日は雨！",
+                "本当？",
+                "「はい。」",
+                "次"
+            ]
+        );
+        assert_eq!(sentences("你好。 再见。"), ["你好。", "再见。"]);
+    }
+
+    #[test]
+    fn test_markdown_markers() {
+        assert_eq!(markdown_marker_len("# Title\n"), Some(2));
+        assert_eq!(markdown_marker_len("  - item"), Some(4));
+        assert_eq!(markdown_marker_len("12. item"), Some(4));
+        assert_eq!(markdown_marker_len("- [x] done"), Some(6));
+        assert_eq!(markdown_marker_len("#hashtag"), None);
+        assert_eq!(markdown_marker_len("---"), None);
+        assert_eq!(markdown_marker_len("3.14 is pi"), None);
+    }
+
+    #[test]
+    fn test_paragraph_motions() {
+        let text = "First para\nstill first.\n\n\n  Second para.\nMore.\n\nThird.";
+        let mut buffer = Buffer::from_str_test(text);
+        let abbreviations = abbreviations();
+        let options = options(&abbreviations, false);
+        let second = text.find("Second").unwrap();
+        let third = text.find("Third").unwrap();
+
+        assert_eq!(paragraph_forward(&mut buffer, 3, &options), second);
+        assert_eq!(paragraph_forward(&mut buffer, second, &options), third);
+        assert_eq!(paragraph_forward(&mut buffer, third, &options), text.len());
+        // From the blank lines and the indentation
+        assert_eq!(paragraph_forward(&mut buffer, 25, &options), second);
+        assert_eq!(paragraph_forward(&mut buffer, second - 1, &options), second);
+
+        assert_eq!(paragraph_backward(&mut buffer, text.len(), &options), third);
+        assert_eq!(paragraph_backward(&mut buffer, third, &options), second);
+        assert_eq!(
+            paragraph_backward(&mut buffer, second + 3, &options),
+            second
+        );
+        assert_eq!(paragraph_backward(&mut buffer, second, &options), 0);
+        assert_eq!(paragraph_backward(&mut buffer, 0, &options), 0);
+    }
+
+    #[test]
+    fn test_markdown_headings_and_list_items_are_paragraphs() {
+        let text = "# Title\nIntro text.\n- one\n  wrapped.\n- two\n";
+        let mut buffer = Buffer::from_str_test(text);
+        let abbreviations = abbreviations();
+        let markdown = options(&abbreviations, true);
+        let intro = text.find("Intro").unwrap();
+        let one = text.find("- one").unwrap();
+        let two = text.find("- two").unwrap();
+
+        assert_eq!(paragraph_forward(&mut buffer, 0, &markdown), intro);
+        assert_eq!(paragraph_forward(&mut buffer, intro, &markdown), one);
+        assert_eq!(paragraph_forward(&mut buffer, one + 8, &markdown), two);
+        assert_eq!(paragraph_backward(&mut buffer, two, &markdown), one);
+
+        // Sentences start after the list marker
+        assert_eq!(sentence_forward(&mut buffer, intro, &markdown), one + 2);
+
+        // Without the markdown rules it's one block
+        let plain = options(&abbreviations, false);
+        assert_eq!(paragraph_forward(&mut buffer, 0, &plain), text.len());
+    }
+
+    #[test]
+    fn test_sentence_motions_cross_paragraphs() {
+        let text = "One. Two e.g. this.\n\nThree. Four";
+        let mut buffer = Buffer::from_str_test(text);
+        let abbreviations = abbreviations();
+        let options = options(&abbreviations, false);
+        let two = text.find("Two").unwrap();
+        let three = text.find("Three").unwrap();
+        let four = text.find("Four").unwrap();
+
+        assert_eq!(sentence_forward(&mut buffer, 0, &options), two);
+        assert_eq!(sentence_forward(&mut buffer, two, &options), three);
+        assert_eq!(sentence_forward(&mut buffer, three, &options), four);
+        assert_eq!(sentence_forward(&mut buffer, four, &options), text.len());
+
+        assert_eq!(sentence_backward(&mut buffer, four + 2, &options), four);
+        assert_eq!(sentence_backward(&mut buffer, four, &options), three);
+        assert_eq!(sentence_backward(&mut buffer, three, &options), two);
+        // From the blank line between the paragraphs
+        assert_eq!(sentence_backward(&mut buffer, three - 1, &options), two);
+        assert_eq!(sentence_backward(&mut buffer, 2, &options), 0);
+    }
+
+    #[test]
+    fn test_sentence_range_and_deletions() {
+        let text = "One. Two here. Three.\n\nNext.";
+        let mut buffer = Buffer::from_str_test(text);
+        let abbreviations = abbreviations();
+        let options = options(&abbreviations, false);
+        let two = text.find("Two").unwrap();
+        let three = text.find("Three").unwrap();
+        let end = text.find('\n').unwrap();
+        let next = text.find("Next").unwrap();
+
+        // With the blanks after it, or before it for the last one
+        assert_eq!(
+            sentence_range(&mut buffer, two + 4, &options),
+            Some(two..three)
+        );
+        assert_eq!(
+            sentence_range(&mut buffer, three + 1, &options),
+            Some(two + 9..end)
+        );
+        assert_eq!(sentence_range(&mut buffer, end + 1, &options), None);
+
+        assert_eq!(
+            sentence_delete_forward(&mut buffer, two + 4, &options),
+            two + 4..three
+        );
+        // The last sentence stops at the paragraph's end, then takes the
+        // lines up to the next one
+        assert_eq!(
+            sentence_delete_forward(&mut buffer, three, &options),
+            three..end
+        );
+        assert_eq!(
+            sentence_delete_forward(&mut buffer, end, &options),
+            end..next
+        );
+
+        assert_eq!(
+            sentence_delete_backward(&mut buffer, two + 4, &options),
+            two..two + 4
+        );
+        assert_eq!(sentence_delete_backward(&mut buffer, two, &options), 0..two);
+        assert_eq!(
+            sentence_delete_backward(&mut buffer, next, &options),
+            end..next
+        );
+
+        assert_eq!(
+            paragraph_delete_forward(&mut buffer, two, &options),
+            two..end
+        );
+        assert_eq!(
+            paragraph_delete_forward(&mut buffer, end, &options),
+            end..next
+        );
+        assert_eq!(
+            paragraph_delete_backward(&mut buffer, two, &options),
+            0..two
+        );
+        assert_eq!(
+            paragraph_delete_backward(&mut buffer, next, &options),
+            end..next
+        );
+    }
+}
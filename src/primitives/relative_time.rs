@@ -0,0 +1,91 @@
+//! Short relative timestamps ("3m ago", "yesterday")
+//!
+//! Times less than a day old are shown as the seconds, minutes or hours
+//! since; a time on the previous calendar day as "yesterday"; anything
+//! older, or in the future, as its date.
+
+use chrono::{DateTime, Local, TimeZone};
+use rust_i18n::t;
+use std::time::SystemTime;
+
+/// Seconds in a minute, an hour and a day
+const MINUTE: u64 = 60;
+const HOUR: u64 = 60 * MINUTE;
+const DAY: u64 = 24 * HOUR;
+
+/// `time` relative to `now`, with calendar days in the local time zone
+pub fn format_relative_time(time: SystemTime, now: SystemTime) -> String {
+    format_relative_time_in(time, now, &Local)
+}
+
+/// `time` relative to `now`, with calendar days in time zone `tz`
+fn format_relative_time_in<Tz: TimeZone>(time: SystemTime, now: SystemTime, tz: &Tz) -> String
+where
+    Tz::Offset: std::fmt::Display,
+{
+    let date = DateTime::<chrono::Utc>::from(time).with_timezone(tz);
+    let Ok(elapsed) = now.duration_since(time) else {
+        return date.format("%Y-%m-%d").to_string();
+    };
+    let secs = elapsed.as_secs();
+    if secs < MINUTE {
+        return t!("time.seconds_ago", count = secs).to_string();
+    }
+    if secs < HOUR {
+        return t!("time.minutes_ago", count = secs / MINUTE).to_string();
+    }
+    if secs < DAY {
+        return t!("time.hours_ago", count = secs / HOUR).to_string();
+    }
+    let today = DateTime::<chrono::Utc>::from(now)
+        .with_timezone(tz)
+        .date_naive();
+    if today.pred_opt() == Some(date.date_naive()) {
+        return t!("time.yesterday").to_string();
+    }
+    date.format("%Y-%m-%d").to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use std::time::Duration;
+
+    /// 2024-03-10 15:00:00 UTC
+    fn now() -> SystemTime {
+        SystemTime::UNIX_EPOCH + Duration::from_secs(1_710_082_800)
+    }
+
+    fn ago(secs: u64) -> String {
+        format_relative_time_in(now() - Duration::from_secs(secs), now(), &Utc)
+    }
+
+    #[test]
+    fn test_within_a_day() {
+        assert_eq!(ago(0), "0s ago");
+        assert_eq!(ago(59), "59s ago");
+        assert_eq!(ago(60), "1m ago");
+        assert_eq!(ago(61), "1m ago");
+        assert_eq!(ago(59 * MINUTE + 59), "59m ago");
+        assert_eq!(ago(HOUR), "1h ago");
+        assert_eq!(ago(23 * HOUR), "23h ago");
+        assert_eq!(ago(DAY - 1), "23h ago");
+    }
+
+    #[test]
+    fn test_older_than_a_day() {
+        // 2024-03-09 at 14:00 and 00:00
+        assert_eq!(ago(25 * HOUR), "yesterday");
+        assert_eq!(ago(39 * HOUR), "yesterday");
+        // 2024-03-08 23:59
+        assert_eq!(ago(39 * HOUR + 60), "2024-03-08");
+        assert_eq!(ago(365 * DAY), "2023-03-11");
+    }
+
+    #[test]
+    fn test_future_time_shows_date() {
+        let later = now() + Duration::from_secs(2 * DAY);
+        assert_eq!(format_relative_time_in(later, now(), &Utc), "2024-03-12");
+    }
+}
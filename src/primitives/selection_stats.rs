@@ -0,0 +1,252 @@
+//! Counts of the selected text
+//!
+//! While text is selected the status bar shows how many lines and
+//! characters are selected. That is drawn every frame, so it never copies
+//! the text: lines come from the line index, characters are counted over
+//! the buffer's pieces, and the result is kept until the selection or the
+//! text changes. Words and grapheme clusters need the text itself; only the
+//! "Selection Statistics" command counts them (see [`TextStats`]).
+
+use crate::model::buffer::{Buffer, LineEnding};
+use crate::model::cursor::Cursors;
+use std::ops::{AddAssign, Range};
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Byte ranges selected by `cursors`, in document order
+///
+/// A block selection contributes one range per line, cut to the line's
+/// length. Empty ranges are left out.
+pub fn selection_regions(buffer: &Buffer, cursors: &Cursors) -> Vec<Range<usize>> {
+    let mut regions = Vec::new();
+    for (_, cursor) in cursors.iter() {
+        match cursor.block_anchor {
+            Some(anchor) if cursor.has_block_selection() => {
+                let line = buffer.get_line_number(cursor.position);
+                let line_start = buffer.line_start_offset(line).unwrap_or(0);
+                let column = cursor.position.saturating_sub(line_start);
+                // Columns are inclusive, as drawn by the renderer
+                let (first_col, last_col) = (anchor.column.min(column), anchor.column.max(column));
+                for row in anchor.line.min(line)..=anchor.line.max(line) {
+                    let Some((start, end)) = line_content_range(buffer, row) else {
+                        continue;
+                    };
+                    let region = (start + first_col).min(end)..(start + last_col + 1).min(end);
+                    if !region.is_empty() {
+                        regions.push(region);
+                    }
+                }
+            }
+            _ => regions.extend(cursor.selection_range().filter(|range| !range.is_empty())),
+        }
+    }
+    regions.sort_by_key(|region| (region.start, region.end));
+    regions
+}
+
+/// Start and end of line `line`, without its line break
+fn line_content_range(buffer: &Buffer, line: usize) -> Option<(usize, usize)> {
+    let start = buffer.line_start_offset(line)?;
+    let end = match buffer.line_start_offset(line + 1) {
+        Some(next) if next > start => {
+            let crlf = buffer.line_ending() == LineEnding::CRLF && next - start >= 2;
+            next - if crlf { 2 } else { 1 }
+        }
+        _ => buffer.len(),
+    };
+    Some((start, end.max(start)))
+}
+
+/// What the status bar shows about the selection
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SelectionSummary {
+    /// Number of selected ranges
+    pub regions: usize,
+    /// Lines touched by the selection, each counted once; None when the
+    /// buffer has no line index
+    pub lines: Option<usize>,
+    /// Characters (Unicode scalar values); None when part of the selection
+    /// isn't loaded
+    pub chars: Option<usize>,
+    /// Bytes selected
+    pub bytes: usize,
+}
+
+impl SelectionSummary {
+    /// Summary of `regions`, which must be in document order; None if they
+    /// are all empty
+    pub fn of(buffer: &Buffer, regions: &[Range<usize>]) -> Option<Self> {
+        let regions: Vec<&Range<usize>> = regions.iter().filter(|r| !r.is_empty()).collect();
+        if regions.is_empty() {
+            return None;
+        }
+
+        let mut lines = Some(0);
+        let mut chars = Some(0);
+        let mut bytes = 0;
+        // Last line counted, so lines shared by regions count once
+        let mut counted_to: Option<usize> = None;
+        for region in &regions {
+            bytes += region.len();
+            chars = chars
+                .zip(buffer.char_count_in_range((*region).clone()))
+                .map(|(total, count)| total + count);
+            lines = lines
+                .zip(region_lines(buffer, region))
+                .map(|(total, (first, last))| {
+                    let first = counted_to.map_or(first, |counted| first.max(counted + 1));
+                    counted_to = Some(counted_to.map_or(last, |counted| counted.max(last)));
+                    total + (last + 1).saturating_sub(first)
+                });
+        }
+        Some(Self {
+            regions: regions.len(),
+            lines,
+            chars,
+            bytes,
+        })
+    }
+}
+
+/// First and last line of a non-empty region; a region ending right after
+/// a line break doesn't touch the next line
+fn region_lines(buffer: &Buffer, region: &Range<usize>) -> Option<(usize, usize)> {
+    let first = buffer.line_at_offset(region.start)?;
+    let last = buffer.line_at_offset(region.end)?;
+    let ends_at_line_start = buffer.line_start_offset(last) == Some(region.end);
+    let last = if ends_at_line_start && last > first {
+        last - 1
+    } else {
+        last
+    };
+    Some((first, last))
+}
+
+/// The selection summary of a buffer, computed again when its selection or
+/// content changes
+#[derive(Debug, Default)]
+pub struct SelectionSummaryCache {
+    revision: u64,
+    regions: Vec<Range<usize>>,
+    summary: Option<SelectionSummary>,
+}
+
+impl SelectionSummaryCache {
+    /// Summary of what `cursors` select in `buffer`; None if nothing is
+    /// selected
+    pub fn get(&mut self, buffer: &Buffer, cursors: &Cursors) -> Option<SelectionSummary> {
+        let regions = selection_regions(buffer, cursors);
+        if regions.is_empty() {
+            self.regions.clear();
+            self.summary = None;
+            return None;
+        }
+        if regions != self.regions || buffer.revision() != self.revision {
+            self.summary = SelectionSummary::of(buffer, &regions);
+            self.regions = regions;
+            self.revision = buffer.revision();
+        }
+        self.summary
+    }
+}
+
+/// Full counts of a piece of text, for "Selection Statistics"
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TextStats {
+    /// Words, found by Unicode word segmentation (UAX #29)
+    pub words: usize,
+    /// Characters as the user sees them: grapheme clusters
+    pub characters: usize,
+    /// Bytes in UTF-8
+    pub bytes: usize,
+    /// Lines; a line break at the end doesn't start another
+    pub lines: usize,
+}
+
+impl TextStats {
+    /// Counts of `text`
+    pub fn of(text: &str) -> Self {
+        Self {
+            words: text.unicode_words().count(),
+            characters: text.graphemes(true).count(),
+            bytes: text.len(),
+            lines: text.lines().count(),
+        }
+    }
+}
+
+/// Lines are added too, so only add up counts of text on separate lines
+impl AddAssign for TextStats {
+    fn add_assign(&mut self, other: Self) {
+        self.words += other.words;
+        self.characters += other.characters;
+        self.bytes += other.bytes;
+        self.lines += other.lines;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::cursor::Cursor;
+
+    fn cursors_selecting(ranges: &[Range<usize>]) -> Cursors {
+        let mut cursors = Cursors::new();
+        *cursors.primary_mut() = Cursor::with_selection(ranges[0].start, ranges[0].end);
+        for range in &ranges[1..] {
+            cursors.add(Cursor::with_selection(range.start, range.end));
+        }
+        cursors
+    }
+
+    #[test]
+    fn test_text_stats_count_graphemes_and_bytes() {
+        // A family emoji is one grapheme of five code points
+        let stats = TextStats::of("hi 👨‍👩‍👧 日本語です\n");
+        assert_eq!(stats.characters, 3 + 2 + 5 + 1);
+        assert_eq!(stats.bytes, "hi 👨‍👩‍👧 日本語です\n".len());
+        assert_eq!(stats.lines, 1);
+    }
+
+    #[test]
+    fn test_text_stats_count_words_across_scripts() {
+        assert_eq!(TextStats::of("The quick brown fox's den").words, 5);
+        // Ideographs count one word each
+        assert_eq!(TextStats::of("東京 is big").words, 4);
+        assert_eq!(TextStats::of("  \n ").words, 0);
+    }
+
+    #[test]
+    fn test_summary_counts_lines_and_chars_without_text() {
+        let buffer = Buffer::from_str_test("one\n日本\nthree\n");
+        // From "ne" through the end of the second line's break
+        let summary = SelectionSummary::of(&buffer, &[1..11]).unwrap();
+        assert_eq!(summary.lines, Some(2));
+        assert_eq!(summary.chars, Some(6));
+        assert_eq!(summary.bytes, 10);
+        assert_eq!(summary.regions, 1);
+    }
+
+    #[test]
+    fn test_summary_totals_regions_counting_shared_lines_once() {
+        let buffer = Buffer::from_str_test("one two\nthree\n");
+        let cursors = cursors_selecting(&[4..7, 0..3, 8..13]);
+        let regions = selection_regions(&buffer, &cursors);
+        assert_eq!(regions, vec![0..3, 4..7, 8..13]);
+
+        let summary = SelectionSummary::of(&buffer, &regions).unwrap();
+        assert_eq!(summary.regions, 3);
+        assert_eq!(summary.lines, Some(2));
+        assert_eq!(summary.chars, Some(11));
+    }
+
+    #[test]
+    fn test_cache_follows_selection() {
+        let buffer = Buffer::from_str_test("hello\n");
+        let mut cache = SelectionSummaryCache::default();
+        let mut cursors = cursors_selecting(&[0..5]);
+        assert_eq!(cache.get(&buffer, &cursors).unwrap().chars, Some(5));
+
+        cursors.primary_mut().clear_selection();
+        assert_eq!(cache.get(&buffer, &cursors), None);
+    }
+}
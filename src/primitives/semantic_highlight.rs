@@ -516,6 +516,7 @@ impl SemanticHighlighter {
                     highlights.push(HighlightSpan {
                         range: range.clone(),
                         color: self.highlight_color,
+                        category: None,
                     });
                 }
             }
@@ -552,6 +553,7 @@ impl SemanticHighlighter {
                         highlights.push(HighlightSpan {
                             range: range.clone(),
                             color: self.highlight_color,
+                            category: None,
                         });
                     }
                 }
@@ -565,6 +567,7 @@ impl SemanticHighlighter {
                     highlights.push(HighlightSpan {
                         range: range.clone(),
                         color: self.highlight_color,
+                        category: None,
                     });
                 }
             }
@@ -664,6 +667,7 @@ impl SemanticHighlighter {
             .map(|(range, _)| HighlightSpan {
                 range,
                 color: self.highlight_color,
+                category: None,
             })
             .collect()
     }
@@ -706,6 +710,7 @@ impl SemanticHighlighter {
             .map(|range| HighlightSpan {
                 range,
                 color: self.highlight_color,
+                category: None,
             })
             .collect()
     }
@@ -68,11 +68,16 @@ impl TextProperty {
 /// Manager for text properties in a buffer
 ///
 /// Stores and queries text properties efficiently. Properties can overlap
-/// and are sorted by start position for fast lookup.
+/// and are sorted by start position; lookups binary search for the
+/// properties starting close enough to the position to reach it, so a panel
+/// with tens of thousands of entries answers in logarithmic time.
 #[derive(Debug, Clone, Default)]
 pub struct TextPropertyManager {
     /// All properties, sorted by start position
     properties: Vec<TextProperty>,
+    /// At least the length of the longest property, bounding how far before
+    /// a position the properties containing it can start
+    max_len: usize,
 }
 
 impl TextPropertyManager {
@@ -80,27 +85,43 @@ impl TextPropertyManager {
     pub fn new() -> Self {
         Self {
             properties: Vec::new(),
+            max_len: 0,
         }
     }
 
     /// Add a text property
     pub fn add(&mut self, property: TextProperty) {
-        // Insert in sorted order by start position
+        // Insert in sorted order by start position, after equal starts
         let pos = self
             .properties
-            .binary_search_by_key(&property.start, |p| p.start)
-            .unwrap_or_else(|e| e);
+            .partition_point(|p| p.start <= property.start);
+        self.max_len = self
+            .max_len
+            .max(property.end.saturating_sub(property.start));
         self.properties.insert(pos, property);
     }
 
+    /// Index of the first property that can reach `pos` or beyond
+    fn first_reaching(&self, pos: usize) -> usize {
+        self.properties
+            .partition_point(|p| p.start + self.max_len <= pos)
+    }
+
     /// Get all properties at a specific byte position
     pub fn get_at(&self, pos: usize) -> Vec<&TextProperty> {
-        self.properties.iter().filter(|p| p.contains(pos)).collect()
+        let end = self.properties.partition_point(|p| p.start <= pos);
+        let start = self.first_reaching(pos).min(end);
+        self.properties[start..end]
+            .iter()
+            .filter(|p| p.contains(pos))
+            .collect()
     }
 
     /// Get all properties overlapping a range
     pub fn get_overlapping(&self, range: &Range<usize>) -> Vec<&TextProperty> {
-        self.properties
+        let end = self.properties.partition_point(|p| p.start < range.end);
+        let start = self.first_reaching(range.start).min(end);
+        self.properties[start..end]
             .iter()
             .filter(|p| p.overlaps(range))
             .collect()
@@ -109,6 +130,7 @@ impl TextPropertyManager {
     /// Clear all properties
     pub fn clear(&mut self) {
         self.properties.clear();
+        self.max_len = 0;
     }
 
     /// Remove all properties in a range
@@ -117,6 +139,35 @@ impl TextPropertyManager {
             .retain(|p| !p.overlaps(range) && !range.contains(&p.start));
     }
 
+    /// Replace the properties in `range` with `replacement`, whose offsets
+    /// start at `range.start` and which covers `new_len` bytes, shifting the
+    /// properties after the range
+    ///
+    /// Only the properties that can reach the range are looked at, so
+    /// appending at the end takes time in the size of the replacement.
+    pub fn splice(&mut self, range: Range<usize>, new_len: usize, replacement: Self) {
+        let first = self.first_reaching(range.start);
+        let tail = self.properties.split_off(first);
+        let (before, after): (Vec<TextProperty>, Vec<TextProperty>) = tail
+            .into_iter()
+            .filter(|p| !p.overlaps(&range) && !range.contains(&p.start))
+            .partition(|p| p.start < range.start);
+        self.properties.extend(before);
+        self.properties
+            .extend(replacement.properties.into_iter().map(|mut property| {
+                property.start += range.start;
+                property.end += range.start;
+                property
+            }));
+        self.properties
+            .extend(after.into_iter().map(|mut property| {
+                property.start = property.start - range.len() + new_len;
+                property.end = property.end - range.len() + new_len;
+                property
+            }));
+        self.max_len = self.max_len.max(replacement.max_len);
+    }
+
     /// Get all properties
     pub fn all(&self) -> &[TextProperty] {
         &self.properties
@@ -132,11 +183,27 @@ impl TextPropertyManager {
         self.properties.len()
     }
 
+    /// Estimated heap memory used by the properties
+    pub fn memory_bytes(&self) -> usize {
+        self.properties.capacity() * std::mem::size_of::<TextProperty>()
+    }
+
+    /// Give back the capacity kept from properties since removed
+    pub fn shrink_to_fit(&mut self) {
+        self.properties.shrink_to_fit();
+    }
+
     /// Set all properties at once (replaces existing)
     pub fn set_all(&mut self, properties: Vec<TextProperty>) {
         self.properties = properties;
         // Ensure sorted by start position
         self.properties.sort_by_key(|p| p.start);
+        self.max_len = self
+            .properties
+            .iter()
+            .map(|p| p.end.saturating_sub(p.start))
+            .max()
+            .unwrap_or(0);
     }
 
     /// Merge properties from another source
@@ -152,13 +219,14 @@ impl TextPropertyManager {
             text.push_str(&entry.text);
             let end = offset + entry.text.len();
 
+            // Entries come in order, so the properties stay sorted
             if !entry.properties.is_empty() {
-                let property = TextProperty {
+                manager.max_len = manager.max_len.max(end - start);
+                manager.properties.push(TextProperty {
                     start,
                     end,
                     properties: entry.properties,
-                };
-                manager.add(property);
+                });
             }
 
             offset = end;
@@ -386,4 +454,69 @@ mod tests {
         assert_eq!(all[0].get("id"), Some(&json!("first")));
         assert_eq!(all[1].get("id"), Some(&json!("third")));
     }
+
+    #[test]
+    fn test_manager_splice() {
+        let mut manager = TextPropertyManager::new();
+        manager.add(TextProperty::new(0, 10).with_property("id", json!("first")));
+        manager.add(TextProperty::new(10, 20).with_property("id", json!("second")));
+        manager.add(TextProperty::new(20, 30).with_property("id", json!("third")));
+
+        // Replace the 10 bytes of "second" with 4 bytes
+        let (_, replacement) = TextPropertyManager::from_entries(vec![
+            TextPropertyEntry::text("ab").with_property("id", json!("new")),
+            TextPropertyEntry::text("cd"),
+        ]);
+        manager.splice(10..20, 4, replacement);
+
+        let all = manager.all();
+        assert_eq!(all.len(), 3);
+        assert_eq!((all[0].start, all[0].end), (0, 10));
+        assert_eq!(all[1].get("id"), Some(&json!("new")));
+        assert_eq!((all[1].start, all[1].end), (10, 12));
+        assert_eq!(all[2].get("id"), Some(&json!("third")));
+        assert_eq!((all[2].start, all[2].end), (14, 24));
+    }
+
+    #[test]
+    fn test_manager_lookup_reaches_long_properties() {
+        let mut manager = TextPropertyManager::new();
+        for i in 0..1000 {
+            manager.add(TextProperty::new(i * 10, i * 10 + 10).with_property("line", json!(i)));
+        }
+        // A header spanning the first half, starting before everything
+        manager.add(TextProperty::new(0, 5000).with_property("id", json!("header")));
+
+        let ids = |props: Vec<&TextProperty>| -> Vec<serde_json::Value> {
+            props
+                .iter()
+                .map(|p| p.get("line").or(p.get("id")).unwrap().clone())
+                .collect()
+        };
+        assert_eq!(ids(manager.get_at(4995)), [json!("header"), json!(499)]);
+        assert_eq!(ids(manager.get_at(5000)), [json!(500)]);
+        assert_eq!(ids(manager.get_at(9999)), [json!(999)]);
+        assert!(manager.get_at(10_000).is_empty());
+        assert_eq!(
+            ids(manager.get_overlapping(&(4990..5010))),
+            [json!("header"), json!(499), json!(500)]
+        );
+    }
+
+    #[test]
+    fn test_manager_splice_appends_at_end() {
+        let (_, mut manager) = TextPropertyManager::from_entries(vec![
+            TextPropertyEntry::text("one\n").with_property("id", json!(1)),
+            TextPropertyEntry::text("two\n").with_property("id", json!(2)),
+        ]);
+        let (_, appended) =
+            TextPropertyManager::from_entries(vec![
+                TextPropertyEntry::text("three\n").with_property("id", json!(3))
+            ]);
+        manager.splice(8..8, 6, appended);
+
+        let spans: Vec<(usize, usize)> = manager.all().iter().map(|p| (p.start, p.end)).collect();
+        assert_eq!(spans, [(0, 4), (4, 8), (8, 14)]);
+        assert_eq!(manager.get_at(10)[0].get("id"), Some(&json!(3)));
+    }
 }
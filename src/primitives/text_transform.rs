@@ -0,0 +1,457 @@
+//! Sorting, de-duplicating and re-casing text
+//!
+//! The pure half of the "Sort Lines", "Unique Lines", "Reverse Lines",
+//! "Shuffle Lines" and case conversion commands. Line transforms take the
+//! text of whole lines and return it rearranged; case conversions take any
+//! text. The editor decides which text that is (see
+//! `app/text_transforms.rs`).
+
+use std::cmp::Ordering;
+use std::collections::HashSet;
+
+/// How "Sort Lines" orders lines
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SortOptions {
+    /// Largest first
+    pub descending: bool,
+    /// Order by the number each line starts with, so "9" comes before "10"
+    pub numeric: bool,
+    /// Ignore case when comparing
+    pub case_insensitive: bool,
+}
+
+impl SortOptions {
+    /// Parse the flags typed in the sort prompt: `d` descending, `n`
+    /// numeric, `i` ignore case, in any order. Empty input sorts ascending.
+    ///
+    /// Returns None if there is any other character.
+    pub fn parse(flags: &str) -> Option<Self> {
+        let mut options = Self::default();
+        for c in flags.chars().filter(|c| !c.is_whitespace()) {
+            match c.to_ascii_lowercase() {
+                'd' => options.descending = true,
+                'n' => options.numeric = true,
+                'i' => options.case_insensitive = true,
+                _ => return None,
+            }
+        }
+        Some(options)
+    }
+}
+
+/// How case conversion re-cases words
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CaseStyle {
+    /// "Title Case Words"
+    Title,
+    /// "camelCase" identifiers
+    Camel,
+    /// "snake_case" identifiers
+    Snake,
+}
+
+/// A text transform command
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextTransform {
+    SortLines(SortOptions),
+    /// Drop repeated lines, keeping the first; with `adjacent_only` only
+    /// runs of the same line are collapsed
+    UniqueLines {
+        adjacent_only: bool,
+    },
+    ReverseLines,
+    ShuffleLines,
+    Case(CaseStyle),
+}
+
+impl TextTransform {
+    /// Whether the transform rearranges whole lines, rather than the exact
+    /// text it is given
+    pub fn works_on_lines(&self) -> bool {
+        !matches!(self, Self::Case(_))
+    }
+
+    /// Transform `text`, whose lines are separated by `line_ending`
+    ///
+    /// `seed` picks the order for "Shuffle Lines" and is ignored otherwise.
+    pub fn apply(&self, text: &str, line_ending: &str, seed: u64) -> String {
+        let lines = || text.split(line_ending).collect::<Vec<_>>();
+        match *self {
+            Self::SortLines(options) => sort_lines(lines(), options).join(line_ending),
+            Self::UniqueLines { adjacent_only } => {
+                unique_lines(lines(), adjacent_only).join(line_ending)
+            }
+            Self::ReverseLines => {
+                let mut lines = lines();
+                lines.reverse();
+                lines.join(line_ending)
+            }
+            Self::ShuffleLines => shuffle_lines(lines(), seed).join(line_ending),
+            Self::Case(style) => convert_case(text, style),
+        }
+    }
+}
+
+/// Sort `lines` stably by `options`
+///
+/// Numeric sorting orders by the number at the start of each line (after
+/// leading whitespace), with lines that don't start with a number first;
+/// lines with equal numbers are ordered by their text.
+pub fn sort_lines(lines: Vec<&str>, options: SortOptions) -> Vec<&str> {
+    let mut keyed: Vec<(Option<f64>, String, &str)> = lines
+        .into_iter()
+        .map(|line| {
+            let number = if options.numeric {
+                leading_number(line)
+            } else {
+                None
+            };
+            let text = if options.case_insensitive {
+                line.to_lowercase()
+            } else {
+                line.to_string()
+            };
+            (number, text, line)
+        })
+        .collect();
+
+    keyed.sort_by(|(a_number, a_text, _), (b_number, b_text, _)| {
+        let by_number = match (a_number, b_number) {
+            (Some(a), Some(b)) => a.total_cmp(b),
+            (None, Some(_)) => Ordering::Less,
+            (Some(_), None) => Ordering::Greater,
+            (None, None) => Ordering::Equal,
+        };
+        let ordering = by_number.then_with(|| a_text.cmp(b_text));
+        if options.descending {
+            ordering.reverse()
+        } else {
+            ordering
+        }
+    });
+    keyed.into_iter().map(|(_, _, line)| line).collect()
+}
+
+/// The number `line` starts with, after leading whitespace: an optional
+/// sign, digits and an optional fraction
+fn leading_number(line: &str) -> Option<f64> {
+    let trimmed = line.trim_start();
+    let bytes = trimmed.as_bytes();
+    let mut end = 0;
+    if matches!(bytes.first(), Some(b'-' | b'+')) {
+        end += 1;
+    }
+    let digits_start = end;
+    while bytes.get(end).is_some_and(u8::is_ascii_digit) {
+        end += 1;
+    }
+    let mut has_digits = end > digits_start;
+    if bytes.get(end) == Some(&b'.') {
+        let fraction_start = end + 1;
+        let mut fraction_end = fraction_start;
+        while bytes.get(fraction_end).is_some_and(u8::is_ascii_digit) {
+            fraction_end += 1;
+        }
+        if fraction_end > fraction_start {
+            has_digits = true;
+            end = fraction_end;
+        }
+    }
+    if !has_digits {
+        return None;
+    }
+    trimmed[..end].parse().ok()
+}
+
+/// Drop repeated lines, keeping the first of each
+pub fn unique_lines(mut lines: Vec<&str>, adjacent_only: bool) -> Vec<&str> {
+    if adjacent_only {
+        lines.dedup();
+    } else {
+        let mut seen = HashSet::new();
+        lines.retain(|line| seen.insert(*line));
+    }
+    lines
+}
+
+/// Shuffle `lines` into an order picked by `seed`
+pub fn shuffle_lines(mut lines: Vec<&str>, seed: u64) -> Vec<&str> {
+    // xorshift64*, which is plenty for rearranging lines
+    let mut state = seed | 1;
+    let mut next = move || {
+        state ^= state >> 12;
+        state ^= state << 25;
+        state ^= state >> 27;
+        state.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    };
+    for i in (1..lines.len()).rev() {
+        let j = (next() % (i as u64 + 1)) as usize;
+        lines.swap(i, j);
+    }
+    lines
+}
+
+/// Re-case `text` in `style`
+///
+/// Title Case capitalizes every word of the text. camelCase and snake_case
+/// convert each identifier (run of letters, digits and underscores) and
+/// leave everything between them alone.
+pub fn convert_case(text: &str, style: CaseStyle) -> String {
+    match style {
+        CaseStyle::Title => to_title_case(text),
+        CaseStyle::Camel | CaseStyle::Snake => {
+            let mut result = String::with_capacity(text.len());
+            let mut rest = text;
+            while let Some(start) = rest.find(is_identifier_char) {
+                result.push_str(&rest[..start]);
+                rest = &rest[start..];
+                let end = rest.find(|c| !is_identifier_char(c)).unwrap_or(rest.len());
+                let identifier = &rest[..end];
+                if style == CaseStyle::Camel {
+                    result.push_str(&to_camel_case(identifier));
+                } else {
+                    result.push_str(&to_snake_case(identifier));
+                }
+                rest = &rest[end..];
+            }
+            result.push_str(rest);
+            result
+        }
+    }
+}
+
+fn is_identifier_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+/// Capitalize the first letter of every word and lowercase the rest
+///
+/// An apostrophe inside a word doesn't start a new one ("don't" becomes
+/// "Don't"), and neither do letters after digits ("3rd").
+pub fn to_title_case(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut in_word = false;
+    for c in text.chars() {
+        if c.is_alphanumeric() {
+            if in_word {
+                result.extend(c.to_lowercase());
+            } else {
+                result.extend(c.to_uppercase());
+            }
+            in_word = true;
+        } else {
+            in_word = in_word && matches!(c, '\'' | '\u{2019}');
+            result.push(c);
+        }
+    }
+    result
+}
+
+/// Convert one identifier to camelCase: "http_server" and "HTTPServer"
+/// both become "httpServer". Leading and trailing underscores are kept.
+pub fn to_camel_case(identifier: &str) -> String {
+    let (prefix, words, suffix) = identifier_words(identifier);
+    let mut result = String::from(prefix);
+    for (i, word) in words.iter().enumerate() {
+        if i == 0 {
+            result.push_str(&word.to_lowercase());
+        } else {
+            let mut chars = word.chars();
+            if let Some(first) = chars.next() {
+                result.extend(first.to_uppercase());
+                result.push_str(&chars.as_str().to_lowercase());
+            }
+        }
+    }
+    result.push_str(suffix);
+    result
+}
+
+/// Convert one identifier to snake_case: "httpServer" and "HTTPServer"
+/// both become "http_server". Leading and trailing underscores are kept.
+pub fn to_snake_case(identifier: &str) -> String {
+    let (prefix, words, suffix) = identifier_words(identifier);
+    let words: Vec<String> = words.iter().map(|word| word.to_lowercase()).collect();
+    format!("{prefix}{}{suffix}", words.join("_"))
+}
+
+/// Split an identifier into its leading underscores, its words and its
+/// trailing underscores
+///
+/// Words are separated by underscores and by case changes: a lowercase
+/// letter or digit followed by an uppercase one ("fooBar", "utf8Decoder"),
+/// or the last capital of an acronym followed by lowercase ("HTTPServer").
+/// Digits stay with the word before them ("utf8", "HTTP2").
+fn identifier_words(identifier: &str) -> (&str, Vec<&str>, &str) {
+    let body = identifier.trim_start_matches('_');
+    let prefix = &identifier[..identifier.len() - body.len()];
+    let trimmed = body.trim_end_matches('_');
+    let suffix = &body[trimmed.len()..];
+
+    let mut words = Vec::new();
+    for part in trimmed.split('_').filter(|part| !part.is_empty()) {
+        let chars: Vec<(usize, char)> = part.char_indices().collect();
+        let mut word_start = 0;
+        for i in 1..chars.len() {
+            let (offset, c) = chars[i];
+            let prev = chars[i - 1].1;
+            let next_is_lower = chars.get(i + 1).is_some_and(|(_, n)| n.is_lowercase());
+            let boundary = c.is_uppercase()
+                && (prev.is_lowercase()
+                    || prev.is_numeric()
+                    || (prev.is_uppercase() && next_is_lower));
+            if boundary {
+                words.push(&part[word_start..offset]);
+                word_start = offset;
+            }
+        }
+        words.push(&part[word_start..]);
+    }
+    (prefix, words, suffix)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sort_options_parse() {
+        assert_eq!(SortOptions::parse(""), Some(SortOptions::default()));
+        assert_eq!(
+            SortOptions::parse("n D"),
+            Some(SortOptions {
+                descending: true,
+                numeric: true,
+                case_insensitive: false,
+            })
+        );
+        assert_eq!(SortOptions::parse("x"), None);
+    }
+
+    #[test]
+    fn test_sort_lines() {
+        let lines = vec!["b", "B", "a", "10", "9"];
+        assert_eq!(
+            sort_lines(lines.clone(), SortOptions::default()),
+            vec!["10", "9", "B", "a", "b"]
+        );
+        let options = SortOptions {
+            case_insensitive: true,
+            ..Default::default()
+        };
+        // Stable: "b" stays before "B"
+        assert_eq!(
+            sort_lines(lines.clone(), options),
+            vec!["10", "9", "a", "b", "B"]
+        );
+        let options = SortOptions {
+            descending: true,
+            ..Default::default()
+        };
+        assert_eq!(sort_lines(lines, options), vec!["b", "a", "B", "9", "10"]);
+    }
+
+    #[test]
+    fn test_sort_lines_numeric() {
+        let options = SortOptions {
+            numeric: true,
+            ..Default::default()
+        };
+        let lines = vec!["10 apples", "  9 pears", "-2.5", "none", "1.5e", "+3"];
+        assert_eq!(
+            sort_lines(lines, options),
+            vec!["none", "-2.5", "1.5e", "+3", "  9 pears", "10 apples"]
+        );
+    }
+
+    #[test]
+    fn test_unique_lines() {
+        let lines = vec!["a", "b", "a", "a", "c", "b"];
+        assert_eq!(unique_lines(lines.clone(), false), vec!["a", "b", "c"]);
+        assert_eq!(unique_lines(lines, true), vec!["a", "b", "a", "c", "b"]);
+    }
+
+    #[test]
+    fn test_shuffle_lines_keeps_lines() {
+        let lines: Vec<String> = (0..50).map(|i| i.to_string()).collect();
+        let lines: Vec<&str> = lines.iter().map(String::as_str).collect();
+        let shuffled = shuffle_lines(lines.clone(), 42);
+        assert_ne!(shuffled, lines);
+        assert_eq!(shuffled, shuffle_lines(lines.clone(), 42));
+        let mut sorted = shuffled;
+        sorted.sort_by_key(|line| line.parse::<u32>().unwrap());
+        assert_eq!(sorted, lines);
+    }
+
+    #[test]
+    fn test_apply_keeps_line_endings() {
+        let transform = TextTransform::SortLines(SortOptions::default());
+        assert_eq!(transform.apply("c\r\na\r\nb", "\r\n", 0), "a\r\nb\r\nc");
+        assert_eq!(
+            TextTransform::ReverseLines.apply("1\n\n2", "\n", 0),
+            "2\n\n1"
+        );
+    }
+
+    #[test]
+    fn test_snake_case() {
+        assert_eq!(to_snake_case("HTTPServer"), "http_server");
+        assert_eq!(to_snake_case("parseHTTPResponse"), "parse_http_response");
+        assert_eq!(to_snake_case("fooBar"), "foo_bar");
+        assert_eq!(to_snake_case("FooBar"), "foo_bar");
+        assert_eq!(to_snake_case("already_snake"), "already_snake");
+        assert_eq!(to_snake_case("SCREAMING_CASE"), "screaming_case");
+        assert_eq!(to_snake_case("__private__"), "__private__");
+        assert_eq!(to_snake_case("_leadingCamel"), "_leading_camel");
+    }
+
+    #[test]
+    fn test_snake_case_digits() {
+        assert_eq!(to_snake_case("utf8Decoder"), "utf8_decoder");
+        assert_eq!(to_snake_case("HTTP2Server"), "http2_server");
+        assert_eq!(to_snake_case("vec3"), "vec3");
+        assert_eq!(to_snake_case("base64"), "base64");
+        assert_eq!(to_snake_case("123"), "123");
+    }
+
+    #[test]
+    fn test_camel_case() {
+        assert_eq!(to_camel_case("http_server"), "httpServer");
+        assert_eq!(to_camel_case("HTTPServer"), "httpServer");
+        assert_eq!(to_camel_case("FooBar"), "fooBar");
+        assert_eq!(to_camel_case("SCREAMING_CASE"), "screamingCase");
+        assert_eq!(to_camel_case("utf8_decoder"), "utf8Decoder");
+        assert_eq!(to_camel_case("_private_field"), "_privateField");
+        assert_eq!(to_camel_case("alreadyCamel"), "alreadyCamel");
+    }
+
+    #[test]
+    fn test_case_conversion_non_ascii() {
+        assert_eq!(to_snake_case("ÉcoleNormale"), "école_normale");
+        assert_eq!(to_camel_case("über_größe"), "überGröße");
+        assert_eq!(to_snake_case("ΣίγμαΤιμή"), "σίγμα_τιμή");
+        // Letters without case never start a word
+        assert_eq!(to_snake_case("名前Value"), "名前value");
+        assert_eq!(to_title_case("élan VITAL"), "Élan Vital");
+    }
+
+    #[test]
+    fn test_title_case() {
+        assert_eq!(to_title_case("hello WORLD"), "Hello World");
+        assert_eq!(to_title_case("don't stop"), "Don't Stop");
+        assert_eq!(to_title_case("3rd-party tools"), "3rd-Party Tools");
+        assert_eq!(to_title_case("  spaced\tout  "), "  Spaced\tOut  ");
+    }
+
+    #[test]
+    fn test_convert_case_leaves_non_identifiers() {
+        assert_eq!(
+            convert_case("let fooBar = HTTPServer::new(x, y);", CaseStyle::Snake),
+            "let foo_bar = http_server::new(x, y);"
+        );
+        assert_eq!(
+            convert_case("get_value(), set_value()", CaseStyle::Camel),
+            "getValue(), setValue()"
+        );
+    }
+}
@@ -0,0 +1,350 @@
+//! Fitting text into a number of terminal columns
+//!
+//! The status bar, the prompt and the prompt's suggestion list all cut text
+//! down to the space they have. Rather than dropping the end, which is often
+//! the interesting part (the file name at the end of a path, the reason at the
+//! end of an error), these helpers shorten the middle of paths first.
+//!
+//! Widths are display columns, see [`crate::primitives::display_width`], so
+//! double-width characters are never split. The ellipsis is passed in, so
+//! callers can use the active glyph table's.
+
+use super::display_width::{char_width, str_width};
+
+/// The longest prefix of `text` that fits in `width` columns
+pub fn take_width(text: &str, width: usize) -> &str {
+    let mut used = 0;
+    for (index, c) in text.char_indices() {
+        used += char_width(c);
+        if used > width {
+            return &text[..index];
+        }
+    }
+    text
+}
+
+/// The longest suffix of `text` that fits in `width` columns
+pub fn take_width_from_end(text: &str, width: usize) -> &str {
+    let mut used = 0;
+    for (index, c) in text.char_indices().rev() {
+        used += char_width(c);
+        if used > width {
+            return &text[index + c.len_utf8()..];
+        }
+    }
+    text
+}
+
+/// Cut the end of `text` so it fits in `max_width` columns, ending in
+/// `ellipsis` if anything was cut
+pub fn truncate_end(text: &str, max_width: usize, ellipsis: &str) -> String {
+    if str_width(text) <= max_width {
+        return text.to_string();
+    }
+    let ellipsis_width = str_width(ellipsis);
+    if max_width < ellipsis_width {
+        return take_width(text, max_width).to_string();
+    }
+    format!(
+        "{}{}",
+        take_width(text, max_width - ellipsis_width),
+        ellipsis
+    )
+}
+
+/// Cut the middle of `text` so it fits in `max_width` columns, putting
+/// `ellipsis` where text was cut
+///
+/// The end gets the extra column when the space left doesn't split evenly.
+pub fn truncate_middle(text: &str, max_width: usize, ellipsis: &str) -> String {
+    if str_width(text) <= max_width {
+        return text.to_string();
+    }
+    let ellipsis_width = str_width(ellipsis);
+    if max_width < ellipsis_width {
+        return take_width(text, max_width).to_string();
+    }
+    let room = max_width - ellipsis_width;
+    let head = take_width(text, room / 2);
+    let tail = take_width_from_end(text, room - str_width(head));
+    format!("{head}{ellipsis}{tail}")
+}
+
+/// Shorten a path to `max_width` columns, keeping its first directory and
+/// its file name and leaving out directories in the middle
+///
+/// `/home/user/project/src/view/ui/status_bar.rs` becomes
+/// `/home/…/ui/status_bar.rs`: the directories nearest the file name are
+/// kept while they fit. A file name that doesn't fit on its own is cut in
+/// the middle, so its extension stays.
+pub fn truncate_path(path: &str, max_width: usize, ellipsis: &str) -> String {
+    if str_width(path) <= max_width {
+        return path.to_string();
+    }
+    let separator = if path.contains('/') { '/' } else { '\\' };
+    let parts: Vec<&str> = path.split(separator).collect();
+    let Some((file_name, dirs)) = parts.split_last() else {
+        return truncate_middle(path, max_width, ellipsis);
+    };
+
+    // The leading empty parts of an absolute path stay with the first
+    // directory, so "/home" keeps its slash
+    let first = dirs.iter().position(|part| !part.is_empty());
+    if let Some(first) = first.filter(|&first| first + 1 < dirs.len()) {
+        let mut head = dirs[..=first].join(&separator.to_string());
+        head.push(separator);
+        head.push_str(ellipsis);
+        let mut tail = format!("{separator}{file_name}");
+        if str_width(&head) + str_width(&tail) <= max_width {
+            // Add back the directories nearest the file name while they fit
+            for dir in dirs[first + 1..].iter().rev() {
+                let longer = format!("{separator}{dir}{tail}");
+                if str_width(&head) + str_width(&longer) > max_width {
+                    break;
+                }
+                tail = longer;
+            }
+            return format!("{head}{tail}");
+        }
+    }
+
+    // Not even the first directory fits: keep what can be of the file name
+    let short = format!("{ellipsis}{separator}{file_name}");
+    if str_width(&short) <= max_width {
+        return short;
+    }
+    truncate_middle(file_name, max_width, ellipsis)
+}
+
+/// Whether a word of a message looks like a file path
+fn is_path_like(word: &str) -> bool {
+    let separators = word.matches(['/', '\\']).count();
+    separators >= 2 || (separators == 1 && !word.starts_with("http"))
+}
+
+/// Fit a message in `max_width` columns, shortening the paths in it first
+///
+/// Paths, the longest first, lose the directories in their middle (see
+/// [`truncate_path`]) but keep their file names. If the message is still too
+/// wide its end is cut.
+pub fn fit_message(text: &str, max_width: usize, ellipsis: &str) -> String {
+    if str_width(text) <= max_width {
+        return text.to_string();
+    }
+
+    // Words as (start, end) byte ranges, split at whitespace
+    let mut words = Vec::new();
+    let mut start = None;
+    for (index, c) in text.char_indices() {
+        match (c.is_whitespace(), start) {
+            (false, None) => start = Some(index),
+            (true, Some(word_start)) => {
+                words.push(word_start..index);
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    if let Some(word_start) = start {
+        words.push(word_start..text.len());
+    }
+
+    let mut paths: Vec<_> = words
+        .into_iter()
+        .filter(|range| is_path_like(&text[range.clone()]))
+        .collect();
+    paths.sort_by_key(|range| std::cmp::Reverse(str_width(&text[range.clone()])));
+
+    let mut shortened: Vec<(std::ops::Range<usize>, String)> = Vec::new();
+    let mut overflow = str_width(text) - max_width;
+    for range in paths {
+        if overflow == 0 {
+            break;
+        }
+        let path = &text[range.clone()];
+        let width = str_width(path);
+        // Never shorter than the file name, which is what the path is for
+        let floor = path.rsplit(['/', '\\']).next().map_or(0, |file_name| {
+            str_width(ellipsis) + 1 + str_width(file_name)
+        });
+        let short = truncate_path(path, width.saturating_sub(overflow).max(floor), ellipsis);
+        let saved = width.saturating_sub(str_width(&short));
+        if saved > 0 {
+            overflow -= saved.min(overflow);
+            shortened.push((range, short));
+        }
+    }
+
+    shortened.sort_by_key(|(range, _)| range.start);
+    let mut result = String::with_capacity(text.len());
+    let mut copied = 0;
+    for (range, short) in shortened {
+        result.push_str(&text[copied..range.start]);
+        result.push_str(&short);
+        copied = range.end;
+    }
+    result.push_str(&text[copied..]);
+    truncate_end(&result, max_width, ellipsis)
+}
+
+/// The part of a single-line input shown in a field too narrow for it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScrollWindow {
+    /// Byte range of the input that is shown
+    pub start: usize,
+    pub end: usize,
+    /// Whether input is hidden before `start`, shown as an indicator in the
+    /// first column
+    pub clipped_left: bool,
+    /// Whether input is hidden after `end`, shown as an indicator in the
+    /// last column
+    pub clipped_right: bool,
+}
+
+impl ScrollWindow {
+    /// Column of the cursor at byte `cursor` within the field
+    pub fn cursor_column(&self, text: &str, cursor: usize) -> usize {
+        let indicator = usize::from(self.clipped_left);
+        indicator + str_width(&text[self.start..cursor.clamp(self.start, text.len())])
+    }
+}
+
+/// The part of `text` to show in a field `width` columns wide so that the
+/// cursor at byte `cursor` stays visible
+///
+/// Text that fits is shown whole. Otherwise the field starts at the
+/// beginning of the text while the cursor is within it, and scrolls to keep
+/// the cursor in its last visible column when the cursor moves past it.
+/// A column at each clipped end is left for an overflow indicator.
+pub fn scroll_window(text: &str, cursor: usize, width: usize) -> ScrollWindow {
+    let cursor = cursor.min(text.len());
+    // The cursor takes a column of its own at the end of the text
+    if str_width(text) < width || width < 3 {
+        return ScrollWindow {
+            start: 0,
+            end: take_width(text, width).len(),
+            clipped_left: false,
+            clipped_right: str_width(text) > width,
+        };
+    }
+
+    let cursor_column = str_width(&text[..cursor]);
+    if cursor_column < width - 1 {
+        let end = take_width(text, width - 1).len();
+        return ScrollWindow {
+            start: 0,
+            end,
+            clipped_left: false,
+            clipped_right: end < text.len(),
+        };
+    }
+
+    // Scrolled: the cursor sits in the last column before the right
+    // indicator, or in the last column when the text ends at the cursor
+    let at_end = cursor == text.len();
+    let content = if at_end { width - 1 } else { width - 2 };
+    let before = take_width_from_end(&text[..cursor], content - 1);
+    let start = cursor - before.len();
+    let end = start + take_width(&text[start..], content).len();
+    ScrollWindow {
+        start,
+        end,
+        clipped_left: true,
+        clipped_right: end < text.len(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_take_width_keeps_wide_chars_whole() {
+        assert_eq!(take_width("日本語", 3), "日");
+        assert_eq!(take_width("日本語", 4), "日本");
+        assert_eq!(take_width_from_end("日本語", 3), "語");
+        assert_eq!(take_width_from_end("abc", 10), "abc");
+    }
+
+    #[test]
+    fn test_truncate_end_and_middle() {
+        assert_eq!(truncate_end("hello world", 20, "…"), "hello world");
+        assert_eq!(truncate_end("hello world", 6, "…"), "hello…");
+        assert_eq!(truncate_end("日本語", 4, "…"), "日…");
+        assert_eq!(truncate_middle("hello world", 7, "…"), "hel…rld");
+        assert_eq!(truncate_middle("hello world", 8, ".."), "hel..rld");
+        assert!(str_width(&truncate_middle("日本語のテキスト", 7, "…")) <= 7);
+    }
+
+    #[test]
+    fn test_truncate_path_keeps_first_dir_and_file_name() {
+        let path = "/home/user/project/src/view/ui/status_bar.rs";
+        assert_eq!(truncate_path(path, 100, "…"), path);
+        assert_eq!(
+            truncate_path(path, 30, "…"),
+            "/home/…/view/ui/status_bar.rs"
+        );
+        assert_eq!(truncate_path(path, 22, "…"), "/home/…/status_bar.rs");
+        assert_eq!(truncate_path(path, 15, "…"), "…/status_bar.rs");
+        assert_eq!(truncate_path(path, 9, "…"), "stat…r.rs");
+        assert_eq!(
+            truncate_path("C:\\Users\\me\\code\\app\\main.rs", 20, "..."),
+            "C:\\...\\app\\main.rs"
+        );
+        assert_eq!(truncate_path("src/a/b/c/lib.rs", 12, "…"), "src/…/lib.rs");
+    }
+
+    #[test]
+    fn test_fit_message_shortens_paths_before_the_end() {
+        let message =
+            "Failed to save /home/user/project/src/deeply/nested/module/file.rs: Permission denied";
+        let fitted = fit_message(message, 60, "…");
+        assert!(str_width(&fitted) <= 60);
+        assert!(fitted.starts_with("Failed to save /home/…/"), "{fitted}");
+        assert!(fitted.ends_with("file.rs: Permission denied"), "{fitted}");
+
+        // Without a path to shorten, the end goes
+        assert_eq!(fit_message("no paths in here", 10, "…"), "no paths …");
+        assert_eq!(fit_message("short", 10, "…"), "short");
+    }
+
+    #[test]
+    fn test_scroll_window_keeps_cursor_visible() {
+        let text = "abcdefghijklmnopqrstuvwxyz";
+
+        // Fits: shown whole
+        let window = scroll_window("abc", 3, 10);
+        assert_eq!((window.start, window.end), (0, 3));
+        assert!(!window.clipped_left && !window.clipped_right);
+
+        // Cursor near the start: the end is clipped
+        let window = scroll_window(text, 2, 10);
+        assert_eq!(&text[window.start..window.end], "abcdefghi");
+        assert!(!window.clipped_left && window.clipped_right);
+        assert_eq!(window.cursor_column(text, 2), 2);
+
+        // Cursor at the end: the start is clipped
+        let window = scroll_window(text, text.len(), 10);
+        assert_eq!(&text[window.start..window.end], "stuvwxyz");
+        assert!(window.clipped_left && !window.clipped_right);
+        assert_eq!(window.cursor_column(text, text.len()), 9);
+
+        // Cursor in the middle: both ends are clipped
+        let window = scroll_window(text, 15, 10);
+        assert!(window.clipped_left && window.clipped_right);
+        assert_eq!(&text[window.start..window.end], "ijklmnop");
+        assert_eq!(window.cursor_column(text, 15), 8);
+    }
+
+    #[test]
+    fn test_scroll_window_with_wide_chars() {
+        let text = "日本語のテキストです";
+        for cursor in text.char_indices().map(|(i, _)| i).chain([text.len()]) {
+            let window = scroll_window(text, cursor, 9);
+            let shown = str_width(&text[window.start..window.end]);
+            let indicators = usize::from(window.clipped_left) + usize::from(window.clipped_right);
+            assert!(shown + indicators <= 9, "cursor {cursor}: {window:?}");
+            assert!(window.cursor_column(text, cursor) < 9, "cursor {cursor}");
+        }
+    }
+}
@@ -10,6 +10,7 @@
 //! - Computation should be sync (editing, rendering)
 //! - Main loop remains responsive and simple
 
+use crate::services::lsp::document_sync::ServerSync;
 use crate::services::terminal::TerminalId;
 use crate::view::file_tree::{FileTreeView, NodeId};
 use lsp_types::{
@@ -23,6 +24,8 @@ use std::sync::mpsc;
 pub enum AsyncMessage {
     /// LSP diagnostics received for a file
     LspDiagnostics {
+        /// Label of the server that published them
+        language: String,
         uri: String,
         diagnostics: Vec<Diagnostic>,
     },
@@ -30,6 +33,24 @@ pub enum AsyncMessage {
     /// LSP server initialized successfully
     LspInitialized { language: String },
 
+    /// Completion trigger characters of an initialized LSP server
+    LspCompletionTriggers {
+        language: String,
+        characters: Vec<String>,
+    },
+
+    /// Code action kinds an initialized LSP server advertises
+    LspCodeActionKinds {
+        language: String,
+        kinds: Vec<String>,
+    },
+
+    /// Position encoding and document sync an initialized LSP server agreed to
+    LspServerSync {
+        language: String,
+        sync: ServerSync,
+    },
+
     /// LSP server crashed or failed
     LspError {
         language: String,
@@ -50,12 +71,28 @@ pub enum AsyncMessage {
         locations: Vec<Location>,
     },
 
+    /// LSP prepareRename response
+    ///
+    /// `supported` is false when the server has no prepareRename; `Ok(None)`
+    /// from a server that has it means there is nothing to rename there.
+    LspPrepareRename {
+        request_id: u64,
+        supported: bool,
+        result: Result<Option<lsp_types::PrepareRenameResponse>, String>,
+    },
+
     /// LSP rename response
     LspRename {
         request_id: u64,
         result: Result<lsp_types::WorkspaceEdit, String>,
     },
 
+    /// LSP on-type formatting response (empty if the server had no edits)
+    LspOnTypeFormatting {
+        request_id: u64,
+        edits: Vec<lsp_types::TextEdit>,
+    },
+
     /// LSP hover response
     LspHover {
         request_id: u64,
@@ -88,6 +125,8 @@ pub enum AsyncMessage {
 
     /// LSP pulled diagnostics response (textDocument/diagnostic)
     LspPulledDiagnostics {
+        /// Label of the server that answered
+        language: String,
         request_id: u64,
         uri: String,
         /// New result_id for incremental updates (None if server doesn't support)
@@ -113,6 +152,15 @@ pub enum AsyncMessage {
     /// File changed externally (future: file watching)
     FileChanged { path: String },
 
+    /// A background file load made progress or finished (poll pending loads)
+    FileLoadProgress { path: std::path::PathBuf },
+
+    /// A background file save made progress or finished (poll pending saves)
+    FileSaveProgress { path: std::path::PathBuf },
+
+    /// A formatter finished (poll pending formats)
+    FormatFinished { path: std::path::PathBuf },
+
     /// Git status updated (future: git integration)
     GitStatusChanged { status: String },
 
@@ -129,6 +177,67 @@ pub enum AsyncMessage {
     /// Contains the updated FileTreeView with the path expanded and selected
     FileExplorerExpandedToPath(FileTreeView),
 
+    /// A background directory listing made progress or finished (poll pending listings)
+    FileExplorerDirProgress { path: std::path::PathBuf },
+
+    /// Number of entries in a collapsed directory, counted for the explorer badge
+    FileExplorerEntryCount {
+        path: std::path::PathBuf,
+        count: usize,
+        modified: Option<std::time::SystemTime>,
+    },
+
+    /// Metadata of explorer entries listed without it (`None` where the
+    /// stat failed)
+    FileExplorerEntryMetadata {
+        entries: Vec<(
+            std::path::PathBuf,
+            Option<crate::services::fs::FsMetadata>,
+        )>,
+    },
+
+    /// Listing a remote directory finished
+    RemoteDirListed {
+        listing_id: u64,
+        result: Result<Vec<crate::services::fs::FsEntry>, String>,
+    },
+
+    /// A directory tree comparison finished
+    DirCompareFinished {
+        compare_id: u64,
+        result: Result<crate::services::dir_compare::DirComparison, String>,
+    },
+
+    /// A project-wide TODO scan finished
+    TodoScanFinished {
+        scan_id: u64,
+        items: Vec<crate::services::todo_scanner::TodoItem>,
+    },
+
+    /// `git blame` for the inline blame finished
+    GitBlameFinished {
+        request_id: u64,
+        path: std::path::PathBuf,
+        result: Result<
+            crate::services::git_blame::BlameSnapshot,
+            crate::services::git_blame::BlameError,
+        >,
+    },
+
+    /// `git show` for "Show Blame Commit" finished
+    GitCommitShown {
+        hash: String,
+        result: Result<String, String>,
+    },
+
+    /// A command run by "Insert Command Output..." finished
+    CommandOutputFinished {
+        request_id: u64,
+        /// Output of each run of the command, or the error of the first
+        /// run that failed
+        result: Result<Vec<String>, String>,
+    },
+
     /// Plugin process completed with output
     PluginProcessOutput {
         /// Unique ID for this process (to match with callback)
@@ -234,6 +343,9 @@ pub enum LspServerStatus {
     Starting,
     Initializing,
     Running,
+    /// Alive, but a request has gone unanswered with nothing else heard
+    /// from the server for a while
+    Unresponsive,
     Error,
     Shutdown,
 }
@@ -258,6 +370,7 @@ impl AsyncBridge {
     /// 1. Main loop processes messages every 16ms (60fps)
     /// 2. LSP messages are infrequent (< 100/sec typically)
     /// 3. Memory usage is bounded by message rate × frame time
+    /// 4. Floods are coalesced once received (see [`crate::services::async_queue`])
     pub fn new() -> Self {
         let (sender, receiver) = mpsc::channel();
         Self {
@@ -420,6 +533,7 @@ mod tests {
 
         sender
             .send(AsyncMessage::LspDiagnostics {
+                language: "rust".to_string(),
                 uri: "file:///test.rs".to_string(),
                 diagnostics: diagnostics.clone(),
             })
@@ -432,6 +546,7 @@ mod tests {
             AsyncMessage::LspDiagnostics {
                 uri,
                 diagnostics: diags,
+                ..
             } => {
                 assert_eq!(uri, "file:///test.rs");
                 assert_eq!(diags.len(), 1);
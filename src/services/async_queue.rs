@@ -0,0 +1,410 @@
+//! Per-source queues for async messages, drained under a budget
+//!
+//! Everything that arrives over the [`AsyncBridge`] is sorted into one queue
+//! per [`MessageSource`]. Each main loop iteration handles messages from the
+//! queues in priority order until it runs out of its count or time budget;
+//! the rest wait for the next iteration, so a flood from one source (a branch
+//! switch touching thousands of files, a chatty language server) can't keep
+//! the loop from getting back to reading input.
+//!
+//! A queue that grows past [`HIGH_WATER_MARK`] is coalesced: of the messages
+//! that only report the latest state of something (a file changed, the
+//! diagnostics of a URI), only the newest one per subject is kept. If a queue
+//! is still longer than [`MAX_QUEUED`] afterwards, its oldest messages that
+//! are safe to lose (progress pings the editor also polls for, log lines)
+//! are dropped until it is half that long. Both are counted in
+//! [`QueueStats`].
+//!
+//! [`AsyncBridge`]: crate::services::async_bridge::AsyncBridge
+
+use crate::services::async_bridge::{AsyncMessage, LspProgressValue};
+use crate::services::terminal::TerminalId;
+use std::collections::{HashSet, VecDeque};
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// Most messages handled in one main loop iteration
+pub const MESSAGE_COUNT_BUDGET: usize = 256;
+
+/// Time after which a main loop iteration stops handling messages
+pub const MESSAGE_TIME_BUDGET: Duration = Duration::from_millis(4);
+
+/// Queue length at which a source's queue is coalesced
+pub const HIGH_WATER_MARK: usize = 1024;
+
+/// Queue length above which droppable messages are discarded
+pub const MAX_QUEUED: usize = 16 * 1024;
+
+/// Where a message comes from, in the order queues are drained
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MessageSource {
+    /// Results of something the user just did (file dialogs, the explorer)
+    Ui,
+    /// Language servers
+    Lsp,
+    /// Plugin responses
+    Plugin,
+    /// Output of plugin processes and terminals
+    Process,
+    /// File system, git and project scans
+    Files,
+}
+
+impl MessageSource {
+    /// All sources, highest priority first
+    pub const ALL: [MessageSource; 5] = [
+        MessageSource::Ui,
+        MessageSource::Lsp,
+        MessageSource::Plugin,
+        MessageSource::Process,
+        MessageSource::Files,
+    ];
+
+    /// Source a message is queued under
+    pub fn of(message: &AsyncMessage) -> Self {
+        use AsyncMessage::*;
+        match message {
+            FileOpenDirectoryLoaded(_)
+            | FileExplorerInitialized(_)
+            | FileExplorerToggleNode(_)
+            | FileExplorerRefreshNode(_)
+            | FileExplorerExpandedToPath(_)
+            | GitCommitShown { .. } => MessageSource::Ui,
+            LspDiagnostics { .. }
+            | LspInitialized { .. }
+            | LspCompletionTriggers { .. }
+            | LspCodeActionKinds { .. }
+            | LspServerSync { .. }
+            | LspError { .. }
+            | LspCompletion { .. }
+            | LspGotoDefinition { .. }
+            | LspPrepareRename { .. }
+            | LspRename { .. }
+            | LspOnTypeFormatting { .. }
+            | LspHover { .. }
+            | LspReferences { .. }
+            | LspSignatureHelp { .. }
+            | LspCodeActions { .. }
+            | LspPulledDiagnostics { .. }
+            | LspInlayHints { .. }
+            | LspServerQuiescent { .. }
+            | LspProgress { .. }
+            | LspWindowMessage { .. }
+            | LspLogMessage { .. }
+            | LspStatusUpdate { .. }
+            | CustomNotification { .. }
+            | LspServerRequest { .. } => MessageSource::Lsp,
+            PluginLspResponse { .. } | PluginResponse(_) => MessageSource::Plugin,
+            PluginProcessOutput { .. }
+            | CommandOutputFinished { .. }
+            | TerminalOutput { .. }
+            | TerminalExited { .. } => MessageSource::Process,
+            FileChanged { .. }
+            | FileLoadProgress { .. }
+            | FileSaveProgress { .. }
+            | FormatFinished { .. }
+            | GitStatusChanged { .. }
+            | FileExplorerDirProgress { .. }
+            | FileExplorerEntryCount { .. }
+            | FileExplorerEntryMetadata { .. }
+            | RemoteDirListed { .. }
+            | DirCompareFinished { .. }
+            | TodoScanFinished { .. }
+            | GitBlameFinished { .. } => MessageSource::Files,
+        }
+    }
+
+    fn index(self) -> usize {
+        self as usize
+    }
+}
+
+/// Subject of a message that only the newest message about is needed for
+#[derive(Debug, PartialEq, Eq, Hash)]
+enum CoalesceKey {
+    FileChanged(String),
+    FileLoadProgress(PathBuf),
+    FileSaveProgress(PathBuf),
+    DirProgress(PathBuf),
+    EntryCount(PathBuf),
+    GitStatus,
+    /// Server label and URI
+    Diagnostics(String, String),
+    LspStatus(String),
+    TerminalOutput(TerminalId),
+}
+
+fn coalesce_key(message: &AsyncMessage) -> Option<CoalesceKey> {
+    use AsyncMessage::*;
+    Some(match message {
+        FileChanged { path } => CoalesceKey::FileChanged(path.clone()),
+        FileLoadProgress { path } => CoalesceKey::FileLoadProgress(path.clone()),
+        FileSaveProgress { path } => CoalesceKey::FileSaveProgress(path.clone()),
+        FileExplorerDirProgress { path } => CoalesceKey::DirProgress(path.clone()),
+        FileExplorerEntryCount { path, .. } => CoalesceKey::EntryCount(path.clone()),
+        GitStatusChanged { .. } => CoalesceKey::GitStatus,
+        LspDiagnostics { language, uri, .. } => {
+            CoalesceKey::Diagnostics(language.clone(), uri.clone())
+        }
+        LspStatusUpdate { language, .. } => CoalesceKey::LspStatus(language.clone()),
+        TerminalOutput { terminal_id } => CoalesceKey::TerminalOutput(*terminal_id),
+        _ => return None,
+    })
+}
+
+/// Whether losing the message only costs a hint the editor can do without
+///
+/// File load, save and listing progress is polled for anyway, auto-revert
+/// also compares modification times, and terminal output only asks for a
+/// redraw.
+fn is_droppable(message: &AsyncMessage) -> bool {
+    use AsyncMessage::*;
+    matches!(
+        message,
+        FileChanged { .. }
+            | FileLoadProgress { .. }
+            | FileSaveProgress { .. }
+            | FileExplorerDirProgress { .. }
+            | TerminalOutput { .. }
+            | LspLogMessage { .. }
+            | LspProgress {
+                value: LspProgressValue::Report { .. },
+                ..
+            }
+    )
+}
+
+/// Counters shown in the performance HUD
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct QueueStats {
+    /// Messages waiting to be handled
+    pub queued: usize,
+    /// Messages replaced by a newer one about the same subject
+    pub coalesced: u64,
+    /// Messages discarded because their queue was full
+    pub dropped: u64,
+}
+
+#[derive(Debug, Default)]
+struct SourceQueue {
+    messages: VecDeque<AsyncMessage>,
+    /// Length at which the queue is coalesced next
+    compact_at: usize,
+}
+
+/// Async messages waiting to be handled, one queue per source
+#[derive(Debug, Default)]
+pub struct AsyncQueue {
+    queues: [SourceQueue; 5],
+    coalesced: u64,
+    dropped: u64,
+}
+
+impl AsyncQueue {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queue a message under its source
+    pub fn push(&mut self, message: AsyncMessage) {
+        let index = MessageSource::of(&message).index();
+        let queue = &mut self.queues[index];
+        queue.messages.push_back(message);
+        if queue.messages.len() >= queue.compact_at.max(HIGH_WATER_MARK) {
+            self.compact(index);
+        }
+    }
+
+    /// Next message to handle, from the highest priority non-empty queue
+    pub fn pop(&mut self) -> Option<AsyncMessage> {
+        self.queues
+            .iter_mut()
+            .find_map(|queue| queue.messages.pop_front())
+    }
+
+    /// Number of messages waiting from `source`
+    pub fn queued(&self, source: MessageSource) -> usize {
+        self.queues[source.index()].messages.len()
+    }
+
+    pub fn len(&self) -> usize {
+        self.queues.iter().map(|queue| queue.messages.len()).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.queues.iter().all(|queue| queue.messages.is_empty())
+    }
+
+    pub fn stats(&self) -> QueueStats {
+        QueueStats {
+            queued: self.len(),
+            coalesced: self.coalesced,
+            dropped: self.dropped,
+        }
+    }
+
+    /// Coalesce a queue that reached its high-water mark, then drop its
+    /// oldest droppable messages if it is longer than [`MAX_QUEUED`]
+    fn compact(&mut self, index: usize) {
+        let queue = &mut self.queues[index];
+        let before = queue.messages.len();
+
+        // Walk from the newest message so the first one seen per key is kept
+        let mut seen = HashSet::new();
+        let mut kept = VecDeque::with_capacity(before);
+        for message in queue.messages.drain(..).rev() {
+            if let Some(key) = coalesce_key(&message) {
+                if !seen.insert(key) {
+                    continue;
+                }
+            }
+            kept.push_front(message);
+        }
+        self.coalesced += (before - kept.len()) as u64;
+
+        if kept.len() > MAX_QUEUED {
+            let mut excess = kept.len() - MAX_QUEUED / 2;
+            let len = kept.len();
+            kept.retain(|message| {
+                if excess > 0 && is_droppable(message) {
+                    excess -= 1;
+                    false
+                } else {
+                    true
+                }
+            });
+            self.dropped += (len - kept.len()) as u64;
+        }
+
+        if before != kept.len() {
+            tracing::debug!(
+                "Compacted {:?} message queue from {} to {}",
+                MessageSource::ALL[index],
+                before,
+                kept.len()
+            );
+        }
+        // Next time once the queue doubled or passed the cap, but never
+        // sooner than HIGH_WATER_MARK messages from now
+        queue.compact_at = (kept.len() * 2)
+            .min(MAX_QUEUED + 1)
+            .max(kept.len() + HIGH_WATER_MARK);
+        queue.messages = kept;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file_changed(path: &str) -> AsyncMessage {
+        AsyncMessage::FileChanged {
+            path: path.to_string(),
+        }
+    }
+
+    fn diagnostics(uri: &str, count: usize) -> AsyncMessage {
+        AsyncMessage::LspDiagnostics {
+            language: "rust".to_string(),
+            uri: uri.to_string(),
+            diagnostics: vec![Default::default(); count],
+        }
+    }
+
+    fn log_message(message: &str) -> AsyncMessage {
+        AsyncMessage::LspLogMessage {
+            language: "rust".to_string(),
+            message_type: crate::services::async_bridge::LspMessageType::Log,
+            message: message.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_sources_are_drained_by_priority() {
+        let mut queue = AsyncQueue::new();
+        queue.push(file_changed("/a"));
+        queue.push(AsyncMessage::TerminalOutput {
+            terminal_id: TerminalId(1),
+        });
+        queue.push(diagnostics("file:///a", 1));
+
+        assert_eq!(queue.len(), 3);
+        assert_eq!(queue.queued(MessageSource::Files), 1);
+        assert!(matches!(
+            queue.pop(),
+            Some(AsyncMessage::LspDiagnostics { .. })
+        ));
+        assert!(matches!(
+            queue.pop(),
+            Some(AsyncMessage::TerminalOutput { .. })
+        ));
+        assert!(matches!(
+            queue.pop(),
+            Some(AsyncMessage::FileChanged { .. })
+        ));
+        assert!(queue.pop().is_none());
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn test_flood_is_coalesced_keeping_newest() {
+        let mut queue = AsyncQueue::new();
+        for i in 0..HIGH_WATER_MARK * 4 {
+            queue.push(file_changed(&format!("/file{}", i % 10)));
+            queue.push(diagnostics("file:///a", i % 7));
+        }
+        assert_eq!(queue.queued(MessageSource::Files), 10);
+        assert_eq!(queue.queued(MessageSource::Lsp), 1);
+        let stats = queue.stats();
+        assert_eq!(stats.queued, 11);
+        assert_eq!(stats.coalesced, (HIGH_WATER_MARK * 8 - 11) as u64);
+        assert_eq!(stats.dropped, 0);
+
+        // The diagnostics that survived are the last ones sent
+        match queue.pop() {
+            Some(AsyncMessage::LspDiagnostics { diagnostics, .. }) => {
+                assert_eq!(diagnostics.len(), (HIGH_WATER_MARK * 4 - 1) % 7)
+            }
+            other => panic!("expected diagnostics, got {:?}", other),
+        }
+        // Files keep the order of their newest change
+        let paths: Vec<String> = std::iter::from_fn(|| queue.pop())
+            .map(|message| match message {
+                AsyncMessage::FileChanged { path } => path,
+                other => panic!("expected a file change, got {:?}", other),
+            })
+            .collect();
+        let expected: Vec<String> = (HIGH_WATER_MARK * 4 - 10..HIGH_WATER_MARK * 4)
+            .map(|i| format!("/file{}", i % 10))
+            .collect();
+        assert_eq!(paths, expected);
+    }
+
+    #[test]
+    fn test_full_queue_drops_only_droppable_messages() {
+        let mut queue = AsyncQueue::new();
+        let count = MAX_QUEUED + 2 * HIGH_WATER_MARK;
+        for i in 0..count {
+            queue.push(log_message(&i.to_string()));
+        }
+        queue.push(AsyncMessage::LspInitialized {
+            language: "rust".to_string(),
+        });
+        let stats = queue.stats();
+        assert!(stats.dropped > 0);
+        assert!(stats.queued <= MAX_QUEUED + 1);
+
+        // The newest log lines and the initialization survive
+        let messages: Vec<AsyncMessage> = std::iter::from_fn(|| queue.pop()).collect();
+        assert!(matches!(
+            messages.last(),
+            Some(AsyncMessage::LspInitialized { .. })
+        ));
+        match &messages[messages.len() - 2] {
+            AsyncMessage::LspLogMessage { message, .. } => {
+                assert_eq!(message, &(count - 1).to_string())
+            }
+            other => panic!("expected a log message, got {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,437 @@
+//! One memory budget for the editor's caches
+//!
+//! Caches that grow with the number and size of open buffers implement
+//! [`BudgetedCache`] and are handed to [`CacheBudget`] on every check. The
+//! budget keeps their sizes, hit rates and when each was last in use, and
+//! when their total exceeds the budget shrinks them, least valuable first:
+//! caches of what isn't on screen go before those of what is, lower
+//! priorities before higher ones, and the least recently used before the
+//! rest.
+//!
+//! Shrinking only drops work that was done ahead: a cache asked for
+//! something it dropped computes it again, as on its first use.
+
+use std::collections::HashMap;
+use std::time::Instant;
+
+/// Budget used when `performance.cache_budget_mb` is 0 and the machine's
+/// memory is unknown
+const FALLBACK_BUDGET_MB: u64 = 256;
+/// Bounds of the budget picked from the machine's memory
+const MIN_AUTO_BUDGET_MB: u64 = 64;
+const MAX_AUTO_BUDGET_MB: u64 = 1024;
+
+/// How much a cache is worth keeping next to the others
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum CachePriority {
+    Low,
+    Normal,
+    High,
+}
+
+/// A cache sharing the memory budget
+pub trait BudgetedCache {
+    /// Kind of cache, as shown in the performance overlay
+    fn name(&self) -> &'static str;
+
+    fn priority(&self) -> CachePriority;
+
+    /// Rough number of bytes the cache holds
+    fn approx_bytes(&self) -> usize;
+
+    /// Lookups answered from the cache and lookups that weren't, since it
+    /// was created
+    fn hits_and_misses(&self) -> (u64, u64) {
+        (0, 0)
+    }
+
+    /// Drop cached data until at most `target_bytes` are left
+    ///
+    /// Caches that can't be shrunk part way drop everything.
+    fn shrink(&mut self, target_bytes: usize);
+}
+
+/// A cache taking part in a budget check
+pub struct BudgetedEntry<'a> {
+    /// Tells caches of the same kind apart between checks, e.g. the id of
+    /// the buffer or split they belong to
+    pub owner: u64,
+    /// Whether what the cache serves is on screen
+    pub in_view: bool,
+    pub cache: &'a mut dyn BudgetedCache,
+}
+
+/// What the budget remembers of a cache between checks
+#[derive(Debug, Clone, Copy)]
+struct Tracked {
+    bytes: usize,
+    hits: u64,
+    misses: u64,
+    last_used: Instant,
+}
+
+/// Totals of one kind of cache
+#[derive(Debug, Clone, PartialEq)]
+pub struct CacheStats {
+    pub name: &'static str,
+    /// Caches of this kind
+    pub count: usize,
+    pub bytes: usize,
+    /// Share of lookups answered from the caches, `None` before the first
+    pub hit_rate: Option<f64>,
+    /// When a cache of this kind was last shrunk
+    pub last_shrink: Option<Instant>,
+}
+
+/// Keeps the editor's caches within one memory budget
+#[derive(Debug)]
+pub struct CacheBudget {
+    budget_bytes: usize,
+    tracked: HashMap<(&'static str, u64), Tracked>,
+    last_shrink: HashMap<&'static str, Instant>,
+}
+
+impl CacheBudget {
+    pub fn new(budget_bytes: usize) -> Self {
+        Self {
+            budget_bytes,
+            tracked: HashMap::new(),
+            last_shrink: HashMap::new(),
+        }
+    }
+
+    /// Budget in bytes for `performance.cache_budget_mb`, where 0 picks one
+    /// from the machine's memory
+    pub fn budget_from_config(cache_budget_mb: u64) -> usize {
+        let mb = match cache_budget_mb {
+            0 => crate::services::process_limits::SystemResources::total_memory_mb()
+                .map(|total| (total / 32).clamp(MIN_AUTO_BUDGET_MB, MAX_AUTO_BUDGET_MB))
+                .unwrap_or(FALLBACK_BUDGET_MB),
+            mb => mb,
+        };
+        (mb as usize).saturating_mul(1024 * 1024)
+    }
+
+    pub fn budget_bytes(&self) -> usize {
+        self.budget_bytes
+    }
+
+    pub fn set_budget_bytes(&mut self, budget_bytes: usize) {
+        self.budget_bytes = budget_bytes;
+    }
+
+    /// Bytes the caches held at the last check
+    pub fn total_bytes(&self) -> usize {
+        self.tracked.values().map(|t| t.bytes).sum()
+    }
+
+    /// Record the caches' sizes and shrink them until they fit the budget
+    ///
+    /// `caches` are all caches sharing the budget; the ones missing since
+    /// the last check are forgotten. Returns the bytes freed.
+    pub fn enforce(&mut self, now: Instant, caches: &mut [BudgetedEntry]) -> usize {
+        self.shrink_to(self.budget_bytes, now, caches)
+    }
+
+    /// Shrink every cache as far as it goes
+    ///
+    /// Returns the bytes freed.
+    pub fn free_all(&mut self, now: Instant, caches: &mut [BudgetedEntry]) -> usize {
+        self.shrink_to(0, now, caches)
+    }
+
+    fn shrink_to(&mut self, target: usize, now: Instant, caches: &mut [BudgetedEntry]) -> usize {
+        self.update(now, caches);
+        let mut excess = self.total_bytes().saturating_sub(target);
+        if excess == 0 {
+            return 0;
+        }
+
+        // Least valuable first; among equals, the larger ones free more
+        let mut order: Vec<usize> = (0..caches.len()).collect();
+        order.sort_by_key(|&i| {
+            let entry = &caches[i];
+            let tracked = &self.tracked[&(entry.cache.name(), entry.owner)];
+            (
+                entry.in_view,
+                entry.cache.priority(),
+                tracked.last_used,
+                std::cmp::Reverse(tracked.bytes),
+            )
+        });
+
+        let mut freed = 0;
+        for i in order {
+            if excess == 0 {
+                break;
+            }
+            let entry = &mut caches[i];
+            let before = entry.cache.approx_bytes();
+            if before == 0 {
+                continue;
+            }
+            entry.cache.shrink(before.saturating_sub(excess));
+            let after = entry.cache.approx_bytes();
+            let released = before.saturating_sub(after);
+            excess = excess.saturating_sub(released);
+            freed += released;
+
+            let name = entry.cache.name();
+            if let Some(tracked) = self.tracked.get_mut(&(name, entry.owner)) {
+                tracked.bytes = after;
+            }
+            if released > 0 {
+                self.last_shrink.insert(name, now);
+            }
+        }
+        if freed > 0 {
+            tracing::debug!("Cache budget freed {} bytes", freed);
+        }
+        freed
+    }
+
+    /// Take in the caches' sizes and counters and forget the ones gone
+    fn update(&mut self, now: Instant, caches: &[BudgetedEntry]) {
+        let mut present = std::collections::HashSet::new();
+        for entry in caches {
+            let key = (entry.cache.name(), entry.owner);
+            let (hits, misses) = entry.cache.hits_and_misses();
+            let tracked = self.tracked.entry(key).or_insert(Tracked {
+                bytes: 0,
+                hits,
+                misses,
+                last_used: now,
+            });
+            // A cache is in use while it's on screen or being looked up
+            if entry.in_view || (hits, misses) != (tracked.hits, tracked.misses) {
+                tracked.last_used = now;
+            }
+            tracked.bytes = entry.cache.approx_bytes();
+            tracked.hits = hits;
+            tracked.misses = misses;
+            present.insert(key);
+        }
+        self.tracked.retain(|key, _| present.contains(key));
+    }
+
+    /// Totals per kind of cache, by name
+    pub fn stats(&self) -> Vec<CacheStats> {
+        let mut by_name: HashMap<&'static str, (usize, usize, u64, u64)> = HashMap::new();
+        for (&(name, _), tracked) in &self.tracked {
+            let totals = by_name.entry(name).or_default();
+            totals.0 += 1;
+            totals.1 += tracked.bytes;
+            totals.2 += tracked.hits;
+            totals.3 += tracked.misses;
+        }
+        let mut stats: Vec<CacheStats> = by_name
+            .into_iter()
+            .map(|(name, (count, bytes, hits, misses))| CacheStats {
+                name,
+                count,
+                bytes,
+                hit_rate: (hits + misses > 0).then(|| hits as f64 / (hits + misses) as f64),
+                last_shrink: self.last_shrink.get(name).copied(),
+            })
+            .collect();
+        stats.sort_by_key(|s| s.name);
+        stats
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+    use std::time::Duration;
+
+    /// Keyed cache of 100-byte values that are computed on a miss
+    struct MockCache {
+        name: &'static str,
+        priority: CachePriority,
+        values: BTreeMap<u32, Vec<u8>>,
+        hits: u64,
+        misses: u64,
+    }
+
+    impl MockCache {
+        fn new(name: &'static str, priority: CachePriority, len: u32) -> Self {
+            let mut cache = Self {
+                name,
+                priority,
+                values: BTreeMap::new(),
+                hits: 0,
+                misses: 0,
+            };
+            for key in 0..len {
+                cache.get(key);
+            }
+            cache
+        }
+
+        fn get(&mut self, key: u32) -> &[u8] {
+            if self.values.contains_key(&key) {
+                self.hits += 1;
+            } else {
+                self.misses += 1;
+                self.values.insert(key, vec![key as u8; 100]);
+            }
+            &self.values[&key]
+        }
+    }
+
+    impl BudgetedCache for MockCache {
+        fn name(&self) -> &'static str {
+            self.name
+        }
+
+        fn priority(&self) -> CachePriority {
+            self.priority
+        }
+
+        fn approx_bytes(&self) -> usize {
+            self.values.len() * 100
+        }
+
+        fn hits_and_misses(&self) -> (u64, u64) {
+            (self.hits, self.misses)
+        }
+
+        fn shrink(&mut self, target_bytes: usize) {
+            while self.approx_bytes() > target_bytes {
+                self.values.pop_first();
+            }
+        }
+    }
+
+    fn entries<'a>(caches: &'a mut [&mut MockCache], in_view: &[bool]) -> Vec<BudgetedEntry<'a>> {
+        caches
+            .iter_mut()
+            .zip(in_view)
+            .enumerate()
+            .map(|(owner, (cache, &in_view))| BudgetedEntry {
+                owner: owner as u64,
+                in_view,
+                cache: &mut **cache,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_within_budget_nothing_is_shrunk() {
+        let mut low = MockCache::new("low", CachePriority::Low, 10);
+        let mut high = MockCache::new("high", CachePriority::High, 10);
+        let mut budget = CacheBudget::new(2000);
+
+        let freed = budget.enforce(
+            Instant::now(),
+            &mut entries(&mut [&mut low, &mut high], &[false, false]),
+        );
+        assert_eq!(freed, 0);
+        assert_eq!(budget.total_bytes(), 2000);
+        assert!(budget.stats().iter().all(|s| s.last_shrink.is_none()));
+    }
+
+    #[test]
+    fn test_shrinks_in_priority_and_recency_order() {
+        let start = Instant::now();
+        let mut low = MockCache::new("low", CachePriority::Low, 10);
+        let mut normal = MockCache::new("normal", CachePriority::Normal, 10);
+        let mut high = MockCache::new("high", CachePriority::High, 10);
+        let mut budget = CacheBudget::new(10_000);
+        budget.enforce(
+            start,
+            &mut entries(&mut [&mut low, &mut normal, &mut high], &[false; 3]),
+        );
+
+        // 3000 bytes against 1500: the low priority cache goes entirely,
+        // the normal one by half, the high one is left alone
+        budget.set_budget_bytes(1500);
+        let later = start + Duration::from_secs(1);
+        let freed = budget.enforce(
+            later,
+            &mut entries(&mut [&mut low, &mut normal, &mut high], &[false; 3]),
+        );
+        assert_eq!(freed, 1500);
+        assert_eq!(low.approx_bytes(), 0);
+        assert_eq!(normal.approx_bytes(), 500);
+        assert_eq!(high.approx_bytes(), 1000);
+        assert_eq!(budget.total_bytes(), 1500);
+
+        let stats = budget.stats();
+        let names: Vec<_> = stats.iter().map(|s| s.name).collect();
+        assert_eq!(names, ["high", "low", "normal"]);
+        assert_eq!(stats[0].last_shrink, None);
+        assert_eq!(stats[1].last_shrink, Some(later));
+        assert_eq!(stats[2].last_shrink, Some(later));
+    }
+
+    #[test]
+    fn test_on_screen_and_recently_used_caches_go_last() {
+        let start = Instant::now();
+        let mut background = MockCache::new("a", CachePriority::High, 10);
+        let mut stale = MockCache::new("b", CachePriority::Low, 10);
+        let mut recent = MockCache::new("c", CachePriority::Low, 10);
+        let mut budget = CacheBudget::new(10_000);
+        budget.enforce(
+            start,
+            &mut entries(
+                &mut [&mut background, &mut stale, &mut recent],
+                &[false, false, false],
+            ),
+        );
+
+        // `recent` is looked up after the first check, `background` is
+        // shown on screen; the high priority of the latter doesn't matter
+        // against being on screen
+        recent.get(0);
+        budget.set_budget_bytes(1500);
+        budget.enforce(
+            start + Duration::from_secs(1),
+            &mut entries(
+                &mut [&mut background, &mut stale, &mut recent],
+                &[true, false, false],
+            ),
+        );
+        assert_eq!(stale.approx_bytes(), 0);
+        assert_eq!(recent.approx_bytes(), 500);
+        assert_eq!(background.approx_bytes(), 1000);
+    }
+
+    #[test]
+    fn test_shrunk_cache_computes_values_again() {
+        let mut cache = MockCache::new("cache", CachePriority::Normal, 10);
+        let mut budget = CacheBudget::new(10_000);
+
+        let freed = budget.free_all(Instant::now(), &mut entries(&mut [&mut cache], &[true]));
+        assert_eq!(freed, 1000);
+        assert_eq!(budget.total_bytes(), 0);
+
+        // Values come back as misses, unchanged
+        assert_eq!(cache.get(3), &[3; 100][..]);
+        assert_eq!(cache.get(3), &[3; 100][..]);
+        assert_eq!(cache.hits_and_misses(), (1, 11));
+
+        budget.enforce(Instant::now(), &mut entries(&mut [&mut cache], &[true]));
+        let stats = budget.stats();
+        assert_eq!(stats[0].bytes, 100);
+        assert_eq!(stats[0].hit_rate, Some(1.0 / 12.0));
+    }
+
+    #[test]
+    fn test_forgets_caches_that_are_gone() {
+        let mut a = MockCache::new("a", CachePriority::Normal, 1);
+        let mut b = MockCache::new("b", CachePriority::Normal, 1);
+        let mut budget = CacheBudget::new(10_000);
+        budget.enforce(
+            Instant::now(),
+            &mut entries(&mut [&mut a, &mut b], &[false, false]),
+        );
+        assert_eq!(budget.stats().len(), 2);
+
+        budget.enforce(Instant::now(), &mut entries(&mut [&mut a], &[false]));
+        let stats = budget.stats();
+        assert_eq!(stats.len(), 1);
+        assert_eq!(stats[0].name, "a");
+    }
+}
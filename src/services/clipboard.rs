@@ -2,27 +2,271 @@
 //!
 //! This module provides a unified clipboard interface that:
 //! - Maintains an internal clipboard for in-editor copy/paste
-//! - Uses crossterm's OSC 52 escape sequences for copying to system clipboard
+//! - Hands copied text to the editor to send as an OSC 52 escape sequence
 //! - Uses arboard crate for reading from system clipboard
 //! - Supports copying HTML-formatted text for rich text editors
 //! - Gracefully falls back to internal clipboard if system clipboard is unavailable
+//! - Keeps a ring of recently copied texts for "Paste from History" and cycle paste
+//!
+//! System clipboard calls run on a worker thread and are given up on after a
+//! timeout, since some providers (wl-clipboard waiting on a portal, an X
+//! selection owner that doesn't answer) can block for many seconds. A provider
+//! that timed out is skipped until [`Clipboard::retry_system`] is called or
+//! [`SLOW_SYSTEM_COOLDOWN`] passes.
 
-use crossterm::clipboard::CopyToClipboard;
-use crossterm::execute;
-use std::io::{stdout, Write};
-use std::sync::Mutex;
+use std::collections::VecDeque;
+use std::sync::{mpsc, Arc, Mutex};
+use std::time::{Duration, Instant};
 
 /// Global clipboard holder to maintain X11 clipboard ownership for the application lifetime.
 /// On X11, the clipboard owner must stay alive to respond to paste requests from other apps.
 static SYSTEM_CLIPBOARD: Mutex<Option<arboard::Clipboard>> = Mutex::new(None);
 
+/// How long a system clipboard that timed out is skipped
+pub const SLOW_SYSTEM_COOLDOWN: Duration = Duration::from_secs(5 * 60);
+
+/// Default time allowed for a system clipboard call
+pub const DEFAULT_SYSTEM_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// Access to the system clipboard
+///
+/// Calls may block; [`Clipboard`] makes them on a worker thread.
+pub trait SystemClipboard: Send + Sync + std::fmt::Debug {
+    /// Name of the provider, for messages
+    fn name(&self) -> String;
+
+    fn set_text(&self, text: &str) -> Result<(), String>;
+
+    fn set_html(&self, html: &str, plain_text: &str) -> Result<(), String>;
+
+    fn get_text(&self) -> Result<String, String>;
+}
+
+/// The system clipboard through arboard
+#[derive(Debug, Default)]
+pub struct ArboardClipboard;
+
+impl ArboardClipboard {
+    /// Run `f` on the shared arboard clipboard, creating it first if needed
+    fn with_clipboard<R>(
+        f: impl FnOnce(&mut arboard::Clipboard) -> Result<R, arboard::Error>,
+    ) -> Result<R, String> {
+        let mut guard = SYSTEM_CLIPBOARD.lock().map_err(|e| e.to_string())?;
+        if guard.is_none() {
+            *guard = Some(arboard::Clipboard::new().map_err(|e| e.to_string())?);
+        }
+        let clipboard = guard.as_mut().expect("clipboard was just created");
+        f(clipboard).map_err(|e| e.to_string())
+    }
+}
+
+impl SystemClipboard for ArboardClipboard {
+    fn name(&self) -> String {
+        if cfg!(target_os = "macos") {
+            "macOS pasteboard".to_string()
+        } else if cfg!(windows) {
+            "Windows clipboard".to_string()
+        } else if std::env::var_os("WAYLAND_DISPLAY").is_some() {
+            "Wayland clipboard".to_string()
+        } else {
+            "X11 selection".to_string()
+        }
+    }
+
+    fn set_text(&self, text: &str) -> Result<(), String> {
+        let result = Self::with_clipboard(|clipboard| clipboard.set_text(text));
+        if let Err(e) = &result {
+            // The clipboard may have lost its connection: try once more with a new one
+            tracing::debug!("arboard copy failed: {}, recreating clipboard", e);
+            if let Ok(mut guard) = SYSTEM_CLIPBOARD.lock() {
+                *guard = None;
+            }
+            return Self::with_clipboard(|clipboard| clipboard.set_text(text));
+        }
+        result
+    }
+
+    fn set_html(&self, html: &str, plain_text: &str) -> Result<(), String> {
+        Self::with_clipboard(|clipboard| clipboard.set_html(html, Some(plain_text)))
+    }
+
+    fn get_text(&self) -> Result<String, String> {
+        Self::with_clipboard(|clipboard| clipboard.get_text())
+    }
+}
+
+/// Whether the system clipboard is used
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SystemClipboardState {
+    /// Used for every operation
+    Available,
+    /// Timed out; skipped until `retry_at`
+    Slow { retry_at: Instant },
+}
+
+/// Maximum number of characters in a ring entry's preview
+const PREVIEW_CHARS: usize = 60;
+
+/// A text in the clipboard ring
+#[derive(Debug, Clone, PartialEq)]
+pub struct RingEntry {
+    text: String,
+    /// First line, truncated to `PREVIEW_CHARS` (computed once, as the text may be large)
+    preview: String,
+}
+
+impl RingEntry {
+    fn new(text: String) -> Self {
+        let first_line = text.lines().next().unwrap_or_default();
+        let mut preview: String = first_line.chars().take(PREVIEW_CHARS).collect();
+        if preview.len() < first_line.len() || first_line.len() < text.trim_end().len() {
+            preview.push('…');
+        }
+        Self { text, preview }
+    }
+
+    /// The full copied text
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Single-line preview of the text
+    pub fn preview(&self) -> &str {
+        &self.preview
+    }
+}
+
+/// Recently copied texts, newest first
+///
+/// Holds at most `capacity` entries and `max_bytes` of text in total; the
+/// oldest entries are evicted first. A text larger than `max_bytes` is not
+/// kept at all.
+#[derive(Debug, Clone)]
+pub struct ClipboardRing {
+    entries: VecDeque<RingEntry>,
+    capacity: usize,
+    max_bytes: usize,
+    bytes: usize,
+    /// Bumped whenever entries change, so indices from before can be detected as stale
+    generation: u64,
+}
+
+impl Default for ClipboardRing {
+    fn default() -> Self {
+        Self::new(10, 16 * 1024 * 1024)
+    }
+}
+
+impl ClipboardRing {
+    /// Create an empty ring
+    pub fn new(capacity: usize, max_bytes: usize) -> Self {
+        Self {
+            entries: VecDeque::new(),
+            capacity,
+            max_bytes,
+            bytes: 0,
+            generation: 0,
+        }
+    }
+
+    /// Change the limits, evicting entries that no longer fit
+    pub fn set_limits(&mut self, capacity: usize, max_bytes: usize) {
+        self.capacity = capacity;
+        self.max_bytes = max_bytes;
+        self.evict();
+    }
+
+    /// Add a text as the newest entry
+    ///
+    /// A text already in the ring moves to the front instead of being added twice.
+    pub fn push(&mut self, text: &str) {
+        if text.is_empty() || text.len() > self.max_bytes {
+            return;
+        }
+        if self.entries.front().is_some_and(|entry| entry.text == text) {
+            return;
+        }
+        if let Some(index) = self.position(text) {
+            if let Some(entry) = self.entries.remove(index) {
+                self.bytes -= entry.text.len();
+            }
+        }
+        self.bytes += text.len();
+        self.entries.push_front(RingEntry::new(text.to_string()));
+        self.generation += 1;
+        self.evict();
+    }
+
+    /// Drop the oldest entries until the ring is within its limits
+    fn evict(&mut self) {
+        while self.entries.len() > self.capacity || self.bytes > self.max_bytes {
+            match self.entries.pop_back() {
+                Some(entry) => {
+                    self.bytes -= entry.text.len();
+                    self.generation += 1;
+                }
+                None => break,
+            }
+        }
+    }
+
+    /// Changes whenever entries are added or evicted
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// Entry by age (0 is the newest)
+    pub fn get(&self, index: usize) -> Option<&RingEntry> {
+        self.entries.get(index)
+    }
+
+    /// Age of the entry holding `text`
+    pub fn position(&self, text: &str) -> Option<usize> {
+        self.entries.iter().position(|entry| entry.text == text)
+    }
+
+    /// Entries, newest first
+    pub fn iter(&self) -> impl Iterator<Item = &RingEntry> {
+        self.entries.iter()
+    }
+
+    /// Number of entries
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether the ring has no entries
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
 /// Clipboard manager that handles both internal and system clipboard
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone)]
 pub struct Clipboard {
     /// Internal clipboard content (always available)
     internal: String,
     /// When true, paste() uses internal clipboard only (for testing)
     internal_only: bool,
+    /// Recently copied texts
+    ring: ClipboardRing,
+    /// The system clipboard provider
+    system: Arc<dyn SystemClipboard>,
+    /// Time allowed for a system clipboard call (zero waits without limit)
+    system_timeout: Duration,
+    system_state: SystemClipboardState,
+    /// Name of the provider that timed out, until the warning about it is taken
+    slow_warning: Option<String>,
+    /// Whether the warning was given this session
+    warned_slow: bool,
+    /// Text copied since the editor last wrote an OSC 52 copy
+    pending_osc52: Option<String>,
+}
+
+impl Default for Clipboard {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Clipboard {
@@ -31,15 +275,120 @@ impl Clipboard {
         Self {
             internal: String::new(),
             internal_only: false,
+            ring: ClipboardRing::default(),
+            system: Arc::new(ArboardClipboard),
+            system_timeout: DEFAULT_SYSTEM_TIMEOUT,
+            system_state: SystemClipboardState::Available,
+            slow_warning: None,
+            warned_slow: false,
+            pending_osc52: None,
         }
     }
 
+    /// Recently copied texts
+    pub fn ring(&self) -> &ClipboardRing {
+        &self.ring
+    }
+
+    /// Recently copied texts, for changing limits or recording pasted text
+    pub fn ring_mut(&mut self) -> &mut ClipboardRing {
+        &mut self.ring
+    }
+
     /// Enable internal-only mode (for testing)
     /// When enabled, paste() uses internal clipboard only, ignoring system clipboard
     pub fn set_internal_only(&mut self, enabled: bool) {
         self.internal_only = enabled;
     }
 
+    /// Whether paste() uses the internal clipboard only
+    pub fn is_internal_only(&self) -> bool {
+        self.internal_only
+    }
+
+    /// Use another system clipboard provider
+    pub fn set_system_clipboard(&mut self, system: Arc<dyn SystemClipboard>) {
+        self.system = system;
+        self.system_state = SystemClipboardState::Available;
+    }
+
+    /// Set the time allowed for a system clipboard call
+    pub fn set_system_timeout(&mut self, timeout: Duration) {
+        self.system_timeout = timeout;
+    }
+
+    /// Whether the system clipboard is used or skipped after timing out
+    pub fn system_state(&self) -> SystemClipboardState {
+        self.system_state
+    }
+
+    /// Use the system clipboard again after it timed out
+    pub fn retry_system(&mut self) {
+        self.system_state = SystemClipboardState::Available;
+    }
+
+    /// Name of the system clipboard provider the first time it timed out this
+    /// session (returned once)
+    pub fn take_slow_warning(&mut self) -> Option<String> {
+        self.slow_warning.take()
+    }
+
+    /// Run `f` on the system clipboard from a worker thread
+    ///
+    /// Returns None when the clipboard is skipped after an earlier timeout or
+    /// doesn't answer in time; the worker is then left to finish on its own.
+    fn with_system<R: Send + 'static>(
+        &mut self,
+        f: impl FnOnce(&dyn SystemClipboard) -> R + Send + 'static,
+    ) -> Option<R> {
+        let now = Instant::now();
+        if let SystemClipboardState::Slow { retry_at } = self.system_state {
+            if now < retry_at {
+                return None;
+            }
+        }
+
+        let system = Arc::clone(&self.system);
+        let (tx, rx) = mpsc::channel();
+        let spawned = std::thread::Builder::new()
+            .name("clipboard".to_string())
+            .spawn(move || {
+                let _ = tx.send(f(system.as_ref()));
+            });
+        if let Err(e) = spawned {
+            tracing::debug!("failed to start clipboard thread: {}", e);
+            return None;
+        }
+
+        let result = if self.system_timeout.is_zero() {
+            rx.recv().ok()
+        } else {
+            rx.recv_timeout(self.system_timeout).ok()
+        };
+        match result {
+            Some(result) => {
+                self.system_state = SystemClipboardState::Available;
+                Some(result)
+            }
+            None => {
+                let name = self.system.name();
+                tracing::warn!(
+                    "system clipboard ({}) did not respond within {:?}, skipping it",
+                    name,
+                    self.system_timeout
+                );
+                self.system_state = SystemClipboardState::Slow {
+                    retry_at: now + SLOW_SYSTEM_COOLDOWN,
+                };
+                if !self.warned_slow {
+                    self.warned_slow = true;
+                    self.slow_warning = Some(name);
+                }
+                None
+            }
+        }
+    }
+
     /// Copy HTML-formatted text to the system clipboard
     ///
     /// Uses arboard to copy HTML with a plain text fallback.
@@ -47,34 +396,22 @@ impl Clipboard {
     /// Returns true if successful, false otherwise.
     pub fn copy_html(&mut self, html: &str, plain_text: &str) -> bool {
         self.internal = plain_text.to_string();
+        self.ring.push(plain_text);
 
-        if let Ok(mut guard) = SYSTEM_CLIPBOARD.lock() {
-            // Create clipboard if it doesn't exist yet
-            if guard.is_none() {
-                match arboard::Clipboard::new() {
-                    Ok(cb) => *guard = Some(cb),
-                    Err(e) => {
-                        tracing::debug!("arboard clipboard init failed for HTML: {}", e);
-                        return false;
-                    }
-                }
+        let html = html.to_string();
+        let plain_text = plain_text.to_string();
+        let len = html.len();
+        match self.with_system(move |system| system.set_html(&html, &plain_text)) {
+            Some(Ok(())) => {
+                tracing::debug!("HTML copied to clipboard ({} bytes)", len);
+                true
             }
-
-            // Try to set HTML on the clipboard
-            if let Some(clipboard) = guard.as_mut() {
-                match clipboard.set_html(html, Some(plain_text)) {
-                    Ok(()) => {
-                        tracing::debug!("HTML copied to clipboard ({} bytes)", html.len());
-                        return true;
-                    }
-                    Err(e) => {
-                        tracing::debug!("arboard HTML copy failed: {}", e);
-                    }
-                }
+            Some(Err(e)) => {
+                tracing::debug!("arboard HTML copy failed: {}", e);
+                false
             }
+            None => false,
         }
-
-        false
     }
 
     /// Copy text to both internal and system clipboard
@@ -84,49 +421,18 @@ impl Clipboard {
     /// 2. arboard crate (works via X11/Wayland APIs in Gnome Console, XFCE Terminal, etc.)
     pub fn copy(&mut self, text: String) {
         self.internal = text.clone();
+        self.ring.push(&text);
 
-        // Try OSC 52 first (works in modern terminals)
+        // OSC 52 (works in modern terminals) is written with the next frame,
+        // see `take_osc52_copy`.
         // Note: This doesn't "fail" in a detectable way - it just sends escape sequences
         // that the terminal may or may not handle
-        let osc52_result = execute!(stdout(), CopyToClipboard::to_clipboard_from(&text));
-        if let Err(e) = &osc52_result {
-            tracing::debug!("Crossterm OSC 52 clipboard copy failed: {}", e);
-        }
-        // Ensure the escape sequence is flushed to the terminal
-        let _ = stdout().flush();
+        self.pending_osc52 = Some(text.clone());
 
         // Also try arboard (works via X11/Wayland in terminals without OSC 52 support)
         // This provides coverage for Gnome Console, XFCE Terminal, and similar
-        //
-        // Important: On X11, the clipboard owner must stay alive to respond to paste requests.
-        // We store the clipboard in a static so it lives for the application lifetime.
-        if let Ok(mut guard) = SYSTEM_CLIPBOARD.lock() {
-            // Create clipboard if it doesn't exist yet
-            if guard.is_none() {
-                match arboard::Clipboard::new() {
-                    Ok(cb) => *guard = Some(cb),
-                    Err(e) => {
-                        tracing::debug!("arboard clipboard init failed: {}", e);
-                    }
-                }
-            }
-
-            // Try to set text on the clipboard
-            if let Some(clipboard) = guard.as_mut() {
-                if let Err(e) = clipboard.set_text(&text) {
-                    tracing::debug!("arboard copy failed: {}, recreating clipboard", e);
-                    // If set_text fails, try recreating the clipboard
-                    drop(guard);
-                    if let Ok(mut guard) = SYSTEM_CLIPBOARD.lock() {
-                        if let Ok(new_clipboard) = arboard::Clipboard::new() {
-                            *guard = Some(new_clipboard);
-                            if let Some(cb) = guard.as_mut() {
-                                let _ = cb.set_text(&text);
-                            }
-                        }
-                    }
-                }
-            }
+        if let Some(Err(e)) = self.with_system(move |system| system.set_text(&text)) {
+            tracing::debug!("system clipboard copy failed: {}", e);
         }
     }
 
@@ -140,31 +446,21 @@ impl Clipboard {
             return self.paste_internal();
         }
 
-        // Try arboard crate via the static clipboard (reads from system clipboard)
-        if let Ok(mut guard) = SYSTEM_CLIPBOARD.lock() {
-            // Create clipboard if it doesn't exist yet
-            if guard.is_none() {
-                if let Ok(cb) = arboard::Clipboard::new() {
-                    *guard = Some(cb);
-                }
-            }
-
-            if let Some(clipboard) = guard.as_mut() {
-                if let Ok(text) = clipboard.get_text() {
-                    if !text.is_empty() {
-                        self.internal = text.clone();
-                        return Some(text);
-                    }
-                }
+        if let Some(Ok(text)) = self.with_system(|system| system.get_text()) {
+            if !text.is_empty() {
+                self.internal = text.clone();
+                return Some(text);
             }
         }
 
         // Fall back to internal clipboard
-        if self.internal.is_empty() {
-            None
-        } else {
-            Some(self.internal.clone())
-        }
+        self.paste_internal()
+    }
+
+    /// Text to copy to the system clipboard with OSC 52, if something was
+    /// copied since the last call
+    pub fn take_osc52_copy(&mut self) -> Option<String> {
+        self.pending_osc52.take()
     }
 
     /// Get the internal clipboard content without checking system clipboard
@@ -188,27 +484,15 @@ impl Clipboard {
     }
 
     /// Check if clipboard is empty (checks both internal and system)
-    pub fn is_empty(&self) -> bool {
+    pub fn is_empty(&mut self) -> bool {
         if !self.internal.is_empty() {
             return false;
         }
 
-        // Check system clipboard via the static clipboard
-        if let Ok(mut guard) = SYSTEM_CLIPBOARD.lock() {
-            if guard.is_none() {
-                if let Ok(cb) = arboard::Clipboard::new() {
-                    *guard = Some(cb);
-                }
-            }
-
-            if let Some(clipboard) = guard.as_mut() {
-                if let Ok(text) = clipboard.get_text() {
-                    return text.is_empty();
-                }
-            }
+        match self.with_system(|system| system.get_text()) {
+            Some(Ok(text)) => text.is_empty(),
+            _ => true,
         }
-
-        true
     }
 }
 
@@ -230,5 +514,145 @@ mod tests {
         let mut clipboard = Clipboard::new();
         clipboard.copy("hello".to_string());
         assert_eq!(clipboard.get_internal(), "hello");
+        assert_eq!(clipboard.ring().get(0).unwrap().text(), "hello");
+    }
+
+    /// A system clipboard that takes `delay` to answer
+    #[derive(Debug)]
+    struct SlowClipboard {
+        delay: Duration,
+        calls: std::sync::atomic::AtomicUsize,
+    }
+
+    impl SlowClipboard {
+        fn calls(&self) -> usize {
+            self.calls.load(std::sync::atomic::Ordering::SeqCst)
+        }
+
+        fn answer(&self) {
+            self.calls.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            std::thread::sleep(self.delay);
+        }
+    }
+
+    impl SystemClipboard for SlowClipboard {
+        fn name(&self) -> String {
+            "slow".to_string()
+        }
+
+        fn set_text(&self, _text: &str) -> Result<(), String> {
+            self.answer();
+            Ok(())
+        }
+
+        fn set_html(&self, _html: &str, _plain_text: &str) -> Result<(), String> {
+            self.answer();
+            Ok(())
+        }
+
+        fn get_text(&self) -> Result<String, String> {
+            self.answer();
+            Ok("system".to_string())
+        }
+    }
+
+    fn slow_clipboard(delay: Duration) -> (Clipboard, Arc<SlowClipboard>) {
+        let system = Arc::new(SlowClipboard {
+            delay,
+            calls: Default::default(),
+        });
+        let mut clipboard = Clipboard::new();
+        clipboard.set_system_clipboard(system.clone());
+        clipboard.set_system_timeout(Duration::from_millis(50));
+        (clipboard, system)
+    }
+
+    #[test]
+    fn test_hanging_system_clipboard_times_out() {
+        let (mut clipboard, system) = slow_clipboard(Duration::from_secs(2));
+
+        let started = Instant::now();
+        clipboard.copy("hello".to_string());
+        assert!(started.elapsed() < Duration::from_secs(1));
+        assert_eq!(clipboard.get_internal(), "hello");
+        assert!(matches!(
+            clipboard.system_state(),
+            SystemClipboardState::Slow { .. }
+        ));
+        assert_eq!(clipboard.take_slow_warning().as_deref(), Some("slow"));
+        assert_eq!(system.calls(), 1);
+
+        // Skipped while slow: paste falls back to the internal register
+        assert_eq!(clipboard.paste().as_deref(), Some("hello"));
+        clipboard.copy("again".to_string());
+        assert_eq!(system.calls(), 1);
+
+        // Tried again after a retry, and only warned once per session
+        clipboard.retry_system();
+        clipboard.copy("third".to_string());
+        assert_eq!(system.calls(), 2);
+        assert!(clipboard.take_slow_warning().is_none());
+    }
+
+    #[test]
+    fn test_slow_system_clipboard_tried_after_cooldown() {
+        let (mut clipboard, system) = slow_clipboard(Duration::ZERO);
+        clipboard.system_state = SystemClipboardState::Slow {
+            retry_at: Instant::now(),
+        };
+
+        assert_eq!(clipboard.paste().as_deref(), Some("system"));
+        assert_eq!(system.calls(), 1);
+        assert_eq!(clipboard.system_state(), SystemClipboardState::Available);
+        assert!(clipboard.take_slow_warning().is_none());
+    }
+
+    fn ring_texts(ring: &ClipboardRing) -> Vec<&str> {
+        ring.iter().map(RingEntry::text).collect()
+    }
+
+    #[test]
+    fn test_ring_evicts_oldest_first() {
+        let mut ring = ClipboardRing::new(3, 1024);
+        for text in ["one", "two", "three", "four"] {
+            ring.push(text);
+        }
+        assert_eq!(ring_texts(&ring), vec!["four", "three", "two"]);
+
+        // Copying a text again moves it to the front rather than duplicating it
+        ring.push("two");
+        assert_eq!(ring_texts(&ring), vec!["two", "four", "three"]);
+
+        ring.set_limits(2, 1024);
+        assert_eq!(ring_texts(&ring), vec!["two", "four"]);
+    }
+
+    #[test]
+    fn test_ring_byte_cap() {
+        let mut ring = ClipboardRing::new(10, 10);
+        ring.push("aaaa");
+        ring.push("bbbb");
+        // Over the cap in total: the oldest entry goes
+        ring.push("cccc");
+        assert_eq!(ring_texts(&ring), vec!["cccc", "bbbb"]);
+        // Larger than the cap on its own: not kept
+        ring.push("dddddddddddd");
+        assert_eq!(ring_texts(&ring), vec!["cccc", "bbbb"]);
+    }
+
+    #[test]
+    fn test_ring_entry_preview() {
+        let long = "x".repeat(100);
+        let mut ring = ClipboardRing::new(10, 1024);
+        ring.push(&long);
+        ring.push("first\nsecond\n");
+        ring.push("single\n");
+        assert_eq!(ring.get(0).unwrap().preview(), "single");
+        assert_eq!(ring.get(1).unwrap().preview(), "first…");
+        assert_eq!(
+            ring.get(2).unwrap().preview(),
+            format!("{}…", "x".repeat(PREVIEW_CHARS))
+        );
+        assert_eq!(ring.get(2).unwrap().text(), long);
     }
 }
@@ -0,0 +1,467 @@
+//! Trust decisions for commands a project asks to run
+//!
+//! A project's `.fresh/config.json` (and `.fresh/session.json`) can name
+//! programs to run: LSP servers, formatters and on-save actions. Opening a
+//! project shouldn't be enough to run them, so every such spawn asks
+//! [`CommandGate::authorize`] first. Commands that the user's own config
+//! names as well are trusted; the others wait until the user allows them.
+//!
+//! Decisions are kept per canonical project path in `command_trust.json` in
+//! the state directory. "Allow once" lasts until the editor exits.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::{ConfigError, FormatterConfig, OnSaveAction};
+use crate::config_io::{ConfigResolver, DirectoryContext};
+use crate::partial_config::PartialConfig;
+use crate::types::LspServerConfig;
+
+/// What a command is run for
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum CommandUse {
+    /// Language server for a language
+    LspServer(String),
+    /// Formatter for a language
+    Formatter(String),
+    /// On-save action for a language
+    OnSave(String),
+}
+
+/// A command about to be spawned
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommandRequest {
+    /// Command and arguments as written in the config (`$FILE` unexpanded)
+    pub command_line: String,
+    pub usage: CommandUse,
+}
+
+impl CommandRequest {
+    pub fn lsp_server(language: &str, config: &LspServerConfig) -> Self {
+        Self {
+            command_line: command_line(&config.command, &config.args),
+            usage: CommandUse::LspServer(language.to_string()),
+        }
+    }
+
+    pub fn formatter(language: &str, formatter: &FormatterConfig) -> Self {
+        Self {
+            command_line: command_line(&formatter.command, &formatter.args),
+            usage: CommandUse::Formatter(language.to_string()),
+        }
+    }
+
+    pub fn on_save(language: &str, action: &OnSaveAction) -> Self {
+        Self {
+            command_line: command_line(&action.command, &action.args),
+            usage: CommandUse::OnSave(language.to_string()),
+        }
+    }
+}
+
+fn command_line(command: &str, args: &[String]) -> String {
+    std::iter::once(command)
+        .chain(args.iter().map(String::as_str))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// The user's answer for a project command
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrustDecision {
+    /// Run it until the editor exits
+    AllowOnce,
+    /// Run it whenever this project asks
+    Always,
+    /// Don't run it for this project
+    Never,
+}
+
+/// A project command that isn't running: waiting for a decision, blocked,
+/// or put off for this session
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProjectCommand {
+    pub command_line: String,
+    /// Config file that names it
+    pub origin: PathBuf,
+    /// What the project config uses it for
+    pub uses: Vec<CommandUse>,
+    /// Blocked with "Never" (rather than not decided yet)
+    pub blocked: bool,
+}
+
+/// Decisions for one project, by command line, with the file that named it
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct ProjectTrust {
+    #[serde(default)]
+    allowed: BTreeMap<String, PathBuf>,
+    #[serde(default)]
+    blocked: BTreeMap<String, PathBuf>,
+}
+
+/// Contents of `command_trust.json`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct TrustFile {
+    /// Keyed by canonical project path
+    #[serde(default)]
+    projects: BTreeMap<String, ProjectTrust>,
+}
+
+#[derive(Debug)]
+struct GateState {
+    store_path: PathBuf,
+    project_key: String,
+    /// Commands the project's config files name, with the file and their uses
+    project_commands: HashMap<String, (PathBuf, Vec<CommandUse>)>,
+    file: TrustFile,
+    allowed_once: HashSet<String>,
+    /// Dismissed without an answer; asked again next session
+    postponed: HashSet<String>,
+    /// Stopped and waiting for a decision, oldest first
+    pending: Vec<String>,
+}
+
+impl GateState {
+    fn project(&self) -> Option<&ProjectTrust> {
+        self.file.projects.get(&self.project_key)
+    }
+
+    fn project_mut(&mut self) -> &mut ProjectTrust {
+        self.file
+            .projects
+            .entry(self.project_key.clone())
+            .or_default()
+    }
+
+    fn command(&self, command_line: &str) -> Option<ProjectCommand> {
+        let stored = self.project().and_then(|p| {
+            p.blocked
+                .get(command_line)
+                .map(|origin| (origin.clone(), true))
+        });
+        let current = self.project_commands.get(command_line);
+        let (origin, blocked) = match (current, stored) {
+            (Some((origin, _)), stored) => (origin.clone(), stored.is_some()),
+            (None, Some(stored)) => stored,
+            (None, None) => return None,
+        };
+        Some(ProjectCommand {
+            command_line: command_line.to_string(),
+            origin,
+            uses: current.map(|(_, uses)| uses.clone()).unwrap_or_default(),
+            blocked,
+        })
+    }
+
+    fn save(&self) -> std::io::Result<()> {
+        if let Some(parent) = self.store_path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let json = serde_json::to_string_pretty(&self.file).map_err(std::io::Error::other)?;
+        std::fs::write(&self.store_path, json)
+    }
+}
+
+/// The one place that decides whether a project command may be spawned
+///
+/// Clones share their state, so the LSP manager and the editor ask the same
+/// gate.
+#[derive(Debug, Clone)]
+pub struct CommandGate {
+    state: Arc<Mutex<GateState>>,
+}
+
+impl CommandGate {
+    /// Read the decisions for `working_dir` and the commands its config names
+    pub fn load(dir_context: &DirectoryContext, working_dir: &Path) -> Self {
+        let store_path = dir_context.command_trust_path();
+        let file = match std::fs::read_to_string(&store_path) {
+            Ok(json) => serde_json::from_str(&json).unwrap_or_else(|e| {
+                tracing::warn!("Ignoring unreadable {:?}: {}", store_path, e);
+                TrustFile::default()
+            }),
+            Err(_) => TrustFile::default(),
+        };
+        let project_key = working_dir
+            .canonicalize()
+            .unwrap_or_else(|_| working_dir.to_path_buf())
+            .to_string_lossy()
+            .into_owned();
+        let gate = Self {
+            state: Arc::new(Mutex::new(GateState {
+                store_path,
+                project_key,
+                project_commands: HashMap::new(),
+                file,
+                allowed_once: HashSet::new(),
+                postponed: HashSet::new(),
+                pending: Vec::new(),
+            })),
+        };
+        gate.refresh(dir_context, working_dir);
+        gate
+    }
+
+    /// Re-read which commands the project's config files name
+    pub fn refresh(&self, dir_context: &DirectoryContext, working_dir: &Path) {
+        let commands = project_commands(dir_context, working_dir);
+        let mut state = self.lock();
+        state.pending.retain(|c| commands.contains_key(c));
+        state.project_commands = commands;
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, GateState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Whether `request` may run now
+    ///
+    /// A project command nobody has decided on yet is held back and queued
+    /// for [`next_pending`](Self::next_pending).
+    pub fn authorize(&self, request: &CommandRequest) -> bool {
+        let mut state = self.lock();
+        let line = &request.command_line;
+        if !state.project_commands.contains_key(line) || state.allowed_once.contains(line) {
+            return true;
+        }
+        if let Some(project) = state.project() {
+            if project.allowed.contains_key(line) {
+                return true;
+            }
+            if project.blocked.contains_key(line) {
+                return false;
+            }
+        }
+        if !state.postponed.contains(line) && !state.pending.contains(line) {
+            tracing::info!(
+                "Holding back project command {:?} ({:?}) until the user decides",
+                line,
+                request.usage
+            );
+            state.pending.push(line.clone());
+        }
+        false
+    }
+
+    /// The oldest command waiting for a decision
+    pub fn next_pending(&self) -> Option<ProjectCommand> {
+        let state = self.lock();
+        state.pending.first().and_then(|line| state.command(line))
+    }
+
+    /// Record the user's decision for a command
+    pub fn decide(&self, command_line: &str, decision: TrustDecision) -> std::io::Result<()> {
+        let mut state = self.lock();
+        state.pending.retain(|c| c != command_line);
+        state.postponed.remove(command_line);
+        let origin = state
+            .command(command_line)
+            .map(|c| c.origin)
+            .unwrap_or_default();
+        let line = command_line.to_string();
+        match decision {
+            TrustDecision::AllowOnce => {
+                state.allowed_once.insert(line.clone());
+                if state.project_mut().blocked.remove(&line).is_none() {
+                    return Ok(());
+                }
+            }
+            TrustDecision::Always => {
+                let project = state.project_mut();
+                project.blocked.remove(&line);
+                project.allowed.insert(line, origin);
+            }
+            TrustDecision::Never => {
+                state.allowed_once.remove(&line);
+                let project = state.project_mut();
+                project.allowed.remove(&line);
+                project.blocked.insert(line, origin);
+            }
+        }
+        state.save()
+    }
+
+    /// Leave a command undecided; it stays held back until the next session
+    pub fn postpone(&self, command_line: &str) {
+        let mut state = self.lock();
+        state.pending.retain(|c| c != command_line);
+        state.postponed.insert(command_line.to_string());
+    }
+
+    /// Commands of this project that are blocked or put off, sorted
+    pub fn held_back(&self) -> Vec<ProjectCommand> {
+        let state = self.lock();
+        let mut lines: Vec<&String> = state.postponed.iter().collect();
+        if let Some(project) = state.project() {
+            lines.extend(project.blocked.keys());
+        }
+        lines.sort();
+        lines.dedup();
+        lines
+            .into_iter()
+            .filter_map(|line| state.command(line))
+            .collect()
+    }
+}
+
+/// Commands named by the project's config files and not by the user's
+fn project_commands(
+    dir_context: &DirectoryContext,
+    working_dir: &Path,
+) -> HashMap<String, (PathBuf, Vec<CommandUse>)> {
+    let resolver = ConfigResolver::new(dir_context.clone(), working_dir.to_path_buf());
+    let load = |layer: Result<Option<PartialConfig>, ConfigError>| {
+        layer.unwrap_or_else(|e| {
+            tracing::warn!("Failed to read config layer for command trust: {}", e);
+            None
+        })
+    };
+
+    let mut user_lines = HashSet::new();
+    for partial in [
+        load(resolver.load_user_layer()),
+        load(resolver.load_user_platform_layer()),
+    ]
+    .into_iter()
+    .flatten()
+    {
+        user_lines.extend(commands_of(&partial).into_iter().map(|r| r.command_line));
+    }
+
+    let mut commands: HashMap<String, (PathBuf, Vec<CommandUse>)> = HashMap::new();
+    let layers = [
+        (
+            resolver.project_config_path(),
+            load(resolver.load_project_layer()),
+        ),
+        (
+            resolver.session_config_path(),
+            load(resolver.load_session_layer()),
+        ),
+    ];
+    for (path, partial) in layers {
+        let Some(partial) = partial else {
+            continue;
+        };
+        for request in commands_of(&partial) {
+            if user_lines.contains(&request.command_line) {
+                continue;
+            }
+            let entry = commands
+                .entry(request.command_line)
+                .or_insert_with(|| (path.clone(), Vec::new()));
+            if !entry.1.contains(&request.usage) {
+                entry.1.push(request.usage);
+            }
+        }
+    }
+    commands
+}
+
+/// Every command a config layer names
+fn commands_of(partial: &PartialConfig) -> Vec<CommandRequest> {
+    let mut requests = Vec::new();
+    for (language, servers) in partial.lsp.iter().flatten() {
+        for server in servers.servers() {
+            requests.push(CommandRequest::lsp_server(language, server));
+        }
+    }
+    for (language, lang) in partial.languages.iter().flatten() {
+        if let Some(formatter) = &lang.formatter {
+            requests.push(CommandRequest::formatter(language, formatter));
+        }
+        for action in lang.on_save.iter().flatten() {
+            requests.push(CommandRequest::on_save(language, action));
+        }
+    }
+    requests
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn project_with_formatter(temp: &TempDir) -> (DirectoryContext, PathBuf) {
+        let dir_context = DirectoryContext::for_testing(temp.path());
+        let project = temp.path().join("project");
+        std::fs::create_dir_all(project.join(".fresh")).unwrap();
+        std::fs::write(
+            project.join(".fresh").join("config.json"),
+            r#"{"languages": {"rust": {"formatter": {"command": "rustfmt", "args": ["--edition", "2021"]}}}}"#,
+        )
+        .unwrap();
+        (dir_context, project)
+    }
+
+    fn rustfmt() -> CommandRequest {
+        CommandRequest {
+            command_line: "rustfmt --edition 2021".to_string(),
+            usage: CommandUse::Formatter("rust".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_undecided_project_command_is_held_back_once() {
+        let temp = TempDir::new().unwrap();
+        let (dir_context, project) = project_with_formatter(&temp);
+        let gate = CommandGate::load(&dir_context, &project);
+
+        assert!(!gate.authorize(&rustfmt()));
+        assert!(!gate.authorize(&rustfmt()));
+        let pending = gate.next_pending().unwrap();
+        assert_eq!(pending.command_line, "rustfmt --edition 2021");
+        assert_eq!(pending.origin, project.join(".fresh").join("config.json"));
+        assert_eq!(pending.uses, vec![CommandUse::Formatter("rust".into())]);
+
+        // Commands the project doesn't name aren't its business
+        assert!(gate.authorize(&CommandRequest {
+            command_line: "rust-analyzer".to_string(),
+            usage: CommandUse::LspServer("rust".to_string()),
+        }));
+    }
+
+    #[test]
+    fn test_user_config_commands_are_trusted() {
+        let temp = TempDir::new().unwrap();
+        let (dir_context, project) = project_with_formatter(&temp);
+        std::fs::create_dir_all(&dir_context.config_dir).unwrap();
+        std::fs::write(
+            dir_context.config_path(),
+            r#"{"languages": {"rust": {"formatter": {"command": "rustfmt", "args": ["--edition", "2021"]}}}}"#,
+        )
+        .unwrap();
+        let gate = CommandGate::load(&dir_context, &project);
+        assert!(gate.authorize(&rustfmt()));
+        assert!(gate.next_pending().is_none());
+    }
+
+    #[test]
+    fn test_decisions_persist_per_project() {
+        let temp = TempDir::new().unwrap();
+        let (dir_context, project) = project_with_formatter(&temp);
+        let gate = CommandGate::load(&dir_context, &project);
+        gate.authorize(&rustfmt());
+        gate.decide("rustfmt --edition 2021", TrustDecision::Never)
+            .unwrap();
+        assert!(gate.next_pending().is_none());
+        assert!(!gate.authorize(&rustfmt()));
+
+        let gate = CommandGate::load(&dir_context, &project);
+        assert!(!gate.authorize(&rustfmt()));
+        assert!(gate.next_pending().is_none());
+        let held_back = gate.held_back();
+        assert_eq!(held_back.len(), 1);
+        assert!(held_back[0].blocked);
+
+        gate.decide("rustfmt --edition 2021", TrustDecision::AllowOnce)
+            .unwrap();
+        assert!(gate.authorize(&rustfmt()));
+        let gate = CommandGate::load(&dir_context, &project);
+        assert!(!gate.authorize(&rustfmt()));
+        assert!(gate.next_pending().is_some());
+    }
+}
@@ -0,0 +1,329 @@
+//! Comparing two directory trees
+//!
+//! [`compare_dirs`] walks both trees with the `ignore` crate, so hidden and
+//! `.gitignore`d files are skipped as in the TODO scan, and gives every file
+//! found on either side a [`FileStatus`]. Files on both sides are compared
+//! by size, then, when the sizes match, by a SHA-256 of their contents.
+//!
+//! The editor runs the comparison on the worker pool ([`start_compare`]),
+//! which reports it in an [`AsyncMessage::DirCompareFinished`]; plugins get
+//! the same comparison from `editor.compareDirectories()`.
+
+use crate::services::async_bridge::AsyncMessage;
+use crate::services::worker_pool::{CancellationToken, JobHandle, JobPriority, WorkerPool};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+/// Bytes read at a time when hashing a file
+const HASH_CHUNK_BYTES: usize = 64 * 1024;
+
+/// How a file compares between the two trees
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FileStatus {
+    OnlyLeft,
+    OnlyRight,
+    Different,
+    Identical,
+}
+
+impl FileStatus {
+    /// Name of the status for plugins and text properties
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::OnlyLeft => "only-left",
+            Self::OnlyRight => "only-right",
+            Self::Different => "different",
+            Self::Identical => "identical",
+        }
+    }
+
+    /// Status named by [`Self::as_str`]
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "only-left" => Some(Self::OnlyLeft),
+            "only-right" => Some(Self::OnlyRight),
+            "different" => Some(Self::Different),
+            "identical" => Some(Self::Identical),
+            _ => None,
+        }
+    }
+}
+
+/// A file found in either tree
+#[derive(Debug, Clone, PartialEq)]
+pub struct ComparedFile {
+    /// Path relative to both roots
+    pub path: PathBuf,
+    pub status: FileStatus,
+}
+
+/// The files of two trees and how they compare
+#[derive(Debug, Clone, PartialEq)]
+pub struct DirComparison {
+    pub left: PathBuf,
+    pub right: PathBuf,
+    /// Files of both trees, sorted by path
+    pub files: Vec<ComparedFile>,
+}
+
+impl DirComparison {
+    /// Number of files with `status`
+    pub fn count(&self, status: FileStatus) -> usize {
+        self.files.iter().filter(|f| f.status == status).count()
+    }
+}
+
+/// A comparison for the worker pool
+pub struct DirCompareRequest {
+    pub compare_id: u64,
+    pub left: PathBuf,
+    pub right: PathBuf,
+    /// Files compared so far, for the progress shown while it runs
+    pub compared: Arc<AtomicUsize>,
+}
+
+/// Compare `left` and `right` on the worker pool
+pub fn start_compare(pool: &WorkerPool, request: DirCompareRequest) -> JobHandle {
+    pool.submit("dir-compare", JobPriority::Normal, move |token| {
+        let result = compare_dirs(&request.left, &request.right, token, &request.compared)
+            .transpose()?
+            .map_err(|e| e.to_string());
+        Some(AsyncMessage::DirCompareFinished {
+            compare_id: request.compare_id,
+            result,
+        })
+    })
+}
+
+/// Compare the trees under `left` and `right`, counting the files compared
+/// in `compared`
+///
+/// Returns `Ok(None)` if `token` was cancelled, and an error if either path
+/// isn't a directory.
+pub fn compare_dirs(
+    left: &Path,
+    right: &Path,
+    token: &CancellationToken,
+    compared: &AtomicUsize,
+) -> io::Result<Option<DirComparison>> {
+    for dir in [left, right] {
+        if !dir.is_dir() {
+            return Err(io::Error::other(format!(
+                "not a directory: {}",
+                dir.display()
+            )));
+        }
+    }
+    let Some(left_files) = walk(left, token) else {
+        return Ok(None);
+    };
+    let Some(mut right_files) = walk(right, token) else {
+        return Ok(None);
+    };
+
+    let mut files = Vec::with_capacity(left_files.len().max(right_files.len()));
+    for (path, left_size) in left_files {
+        if token.is_cancelled() {
+            return Ok(None);
+        }
+        let status = match right_files.remove(&path) {
+            None => FileStatus::OnlyLeft,
+            Some(right_size) if right_size != left_size => FileStatus::Different,
+            Some(_) => match same_contents(&left.join(&path), &right.join(&path), token) {
+                Some(true) => FileStatus::Identical,
+                Some(false) => FileStatus::Different,
+                None => return Ok(None),
+            },
+        };
+        compared.fetch_add(1, Ordering::Relaxed);
+        files.push(ComparedFile { path, status });
+    }
+    for path in right_files.into_keys() {
+        compared.fetch_add(1, Ordering::Relaxed);
+        files.push(ComparedFile {
+            path,
+            status: FileStatus::OnlyRight,
+        });
+    }
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+
+    Ok(Some(DirComparison {
+        left: left.to_path_buf(),
+        right: right.to_path_buf(),
+        files,
+    }))
+}
+
+/// Sizes of the files under `root` by relative path, or `None` if cancelled
+fn walk(root: &Path, token: &CancellationToken) -> Option<BTreeMap<PathBuf, u64>> {
+    let mut files = BTreeMap::new();
+    let walker = ignore::WalkBuilder::new(root).require_git(false).build();
+    for entry in walker.flatten() {
+        if token.is_cancelled() {
+            return None;
+        }
+        if !entry.file_type().is_some_and(|t| t.is_file()) {
+            continue;
+        }
+        let Ok(relative) = entry.path().strip_prefix(root) else {
+            continue;
+        };
+        let size = entry.metadata().map(|m| m.len()).unwrap_or(0);
+        files.insert(relative.to_path_buf(), size);
+    }
+    Some(files)
+}
+
+/// Whether two files of the same size have the same contents, or `None` if
+/// cancelled; a file that can't be read differs
+fn same_contents(left: &Path, right: &Path, token: &CancellationToken) -> Option<bool> {
+    let left_hash = file_hash(left, token);
+    let right_hash = file_hash(right, token);
+    if token.is_cancelled() {
+        return None;
+    }
+    match (left_hash, right_hash) {
+        (Ok(left_hash), Ok(right_hash)) => Some(left_hash == right_hash),
+        (Err(e), _) | (_, Err(e)) => {
+            tracing::debug!(
+                "Can't compare {} with {}: {}",
+                left.display(),
+                right.display(),
+                e
+            );
+            Some(false)
+        }
+    }
+}
+
+/// SHA-256 of a file's contents, stopping early if cancelled
+fn file_hash(path: &Path, token: &CancellationToken) -> io::Result<[u8; 32]> {
+    let mut file = std::fs::File::open(path)?;
+    let mut hasher = Sha256::new();
+    let mut chunk = vec![0; HASH_CHUNK_BYTES];
+    loop {
+        if token.is_cancelled() {
+            return Err(io::Error::other("cancelled"));
+        }
+        let read = file.read(&mut chunk)?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&chunk[..read]);
+    }
+    Ok(hasher.finalize().into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn write(root: &Path, path: &str, contents: &str) {
+        let path = root.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, contents).unwrap();
+    }
+
+    fn statuses(comparison: &DirComparison) -> Vec<(String, FileStatus)> {
+        comparison
+            .files
+            .iter()
+            .map(|f| (f.path.to_string_lossy().replace('\\', "/"), f.status))
+            .collect()
+    }
+
+    #[test]
+    fn test_compare_dirs_gives_each_status() {
+        let temp = TempDir::new().unwrap();
+        let (left, right) = (temp.path().join("left"), temp.path().join("right"));
+        write(&left, "same.txt", "same");
+        write(&right, "same.txt", "same");
+        write(&left, "src/sized.rs", "one");
+        write(&right, "src/sized.rs", "two");
+        write(&left, "src/grown.rs", "short");
+        write(&right, "src/grown.rs", "much longer");
+        write(&left, "src/old.rs", "gone");
+        write(&right, "docs/new.md", "added");
+
+        let compared = AtomicUsize::new(0);
+        let comparison = compare_dirs(&left, &right, &CancellationToken::new(), &compared)
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            statuses(&comparison),
+            vec![
+                ("docs/new.md".to_string(), FileStatus::OnlyRight),
+                ("same.txt".to_string(), FileStatus::Identical),
+                ("src/grown.rs".to_string(), FileStatus::Different),
+                ("src/old.rs".to_string(), FileStatus::OnlyLeft),
+                ("src/sized.rs".to_string(), FileStatus::Different),
+            ]
+        );
+        assert_eq!(compared.load(Ordering::Relaxed), 5);
+        assert_eq!(comparison.count(FileStatus::Different), 2);
+    }
+
+    #[test]
+    fn test_compare_dirs_skips_ignored_files() {
+        let temp = TempDir::new().unwrap();
+        let (left, right) = (temp.path().join("left"), temp.path().join("right"));
+        write(&left, ".gitignore", "out/\n");
+        write(&right, ".gitignore", "out/\n");
+        write(&left, "out/generated.txt", "left");
+        write(&right, "out/generated.txt", "right");
+        write(&left, ".hidden", "left");
+
+        let comparison = compare_dirs(
+            &left,
+            &right,
+            &CancellationToken::new(),
+            &AtomicUsize::new(0),
+        )
+        .unwrap()
+        .unwrap();
+        assert!(comparison.files.is_empty(), "{:?}", comparison.files);
+    }
+
+    #[test]
+    fn test_compare_dirs_needs_directories_and_stops_when_cancelled() {
+        let temp = TempDir::new().unwrap();
+        write(temp.path(), "file.txt", "not a directory");
+        let token = CancellationToken::new();
+        let compared = AtomicUsize::new(0);
+        assert!(compare_dirs(
+            temp.path(),
+            &temp.path().join("file.txt"),
+            &token,
+            &compared
+        )
+        .is_err());
+        assert!(
+            compare_dirs(temp.path(), &temp.path().join("missing"), &token, &compared).is_err()
+        );
+
+        token.cancel();
+        assert_eq!(
+            compare_dirs(temp.path(), temp.path(), &token, &compared).unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_status_names_round_trip() {
+        for status in [
+            FileStatus::OnlyLeft,
+            FileStatus::OnlyRight,
+            FileStatus::Different,
+            FileStatus::Identical,
+        ] {
+            assert_eq!(FileStatus::from_name(status.as_str()), Some(status));
+        }
+        assert_eq!(FileStatus::from_name("renamed"), None);
+    }
+}
@@ -0,0 +1,229 @@
+//! Background listing of large directories
+//!
+//! Expanding a directory with tens of thousands of entries (node_modules, a
+//! photo dump) used to block the main loop until every entry had been read
+//! and stat'ed. A [`DirLoad`] lists the directory on the worker pool instead
+//! and hands the entries over in batches of [`DIR_BATCH_SIZE`], so the file
+//! explorer can show the first entries right away and append the rest as
+//! they arrive, one batch per poll so no frame takes on the whole listing.
+//! Entries come without metadata; the explorer stats only the ones it shows.
+
+use crate::services::async_bridge::AsyncMessage;
+use crate::services::fs::{FsEntry, FsManager};
+use crate::services::worker_pool::{JobHandle, JobPriority, WorkerPool};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{mpsc, Arc};
+use std::time::Duration;
+
+/// Number of entries read between updates
+pub const DIR_BATCH_SIZE: usize = 500;
+
+enum DirEvent {
+    Batch { entries: Vec<FsEntry>, last: bool },
+    Failed(io::Error),
+}
+
+/// The next batch of a listing
+#[derive(Debug, Default)]
+pub struct DirProgress {
+    /// New entries, in no particular order
+    pub entries: Vec<FsEntry>,
+    /// Set once the listing has ended
+    pub finished: Option<io::Result<()>>,
+}
+
+/// A directory being listed in the background
+///
+/// Dropping the load cancels the listing.
+pub struct DirLoad {
+    path: PathBuf,
+    loaded: usize,
+    receiver: mpsc::Receiver<DirEvent>,
+    job: JobHandle,
+}
+
+impl DirLoad {
+    /// Start listing `path` on the worker pool
+    ///
+    /// The file system is accessed through `fs_manager` on `runtime`. If
+    /// `wake` is given, an [`AsyncMessage::FileExplorerDirProgress`] is sent
+    /// after every batch so the main loop polls the load promptly.
+    pub fn start(
+        pool: &WorkerPool,
+        runtime: tokio::runtime::Handle,
+        fs_manager: Arc<FsManager>,
+        path: &Path,
+        wake: Option<mpsc::Sender<AsyncMessage>>,
+    ) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        let job_path = path.to_path_buf();
+
+        let job = pool.submit("dir-load", JobPriority::UserBlocking, move |token| {
+            let notify = || {
+                if let Some(wake) = &wake {
+                    let _ = wake.send(AsyncMessage::FileExplorerDirProgress {
+                        path: job_path.clone(),
+                    });
+                }
+            };
+            let result = runtime.block_on(async {
+                let mut remaining = fs_manager.list_dir(job_path.clone()).await?;
+                loop {
+                    if token.is_cancelled() {
+                        return Ok(());
+                    }
+                    let rest = remaining.split_off(remaining.len().min(DIR_BATCH_SIZE));
+                    let batch = std::mem::replace(&mut remaining, rest);
+                    let last = remaining.is_empty();
+                    let _ = sender.send(DirEvent::Batch {
+                        entries: batch,
+                        last,
+                    });
+                    notify();
+                    if last {
+                        return Ok(());
+                    }
+                }
+            });
+            if let Err(e) = result {
+                let _ = sender.send(DirEvent::Failed(e));
+                notify();
+            }
+            None
+        });
+
+        Self {
+            path: path.to_path_buf(),
+            loaded: 0,
+            receiver,
+            job,
+        }
+    }
+
+    /// Directory being listed
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Number of entries received so far
+    pub fn loaded(&self) -> usize {
+        self.loaded
+    }
+
+    /// Take the next batch if it has arrived, without blocking
+    pub fn poll(&mut self) -> DirProgress {
+        let mut progress = DirProgress::default();
+        match self.receiver.try_recv() {
+            Ok(event) => self.handle(event, &mut progress),
+            Err(mpsc::TryRecvError::Empty) => {}
+            Err(mpsc::TryRecvError::Disconnected) => {
+                progress.finished = Some(Err(self.abandoned()));
+            }
+        }
+        progress
+    }
+
+    /// Block for up to `timeout` waiting for the next batch
+    pub fn wait(&mut self, timeout: Duration) -> DirProgress {
+        let mut progress = DirProgress::default();
+        match self.receiver.recv_timeout(timeout) {
+            Ok(event) => self.handle(event, &mut progress),
+            Err(mpsc::RecvTimeoutError::Timeout) => {}
+            Err(mpsc::RecvTimeoutError::Disconnected) => {
+                progress.finished = Some(Err(self.abandoned()));
+            }
+        }
+        progress
+    }
+
+    fn handle(&mut self, event: DirEvent, progress: &mut DirProgress) {
+        match event {
+            DirEvent::Batch { entries, last } => {
+                self.loaded += entries.len();
+                progress.entries.extend(entries);
+                if last {
+                    progress.finished = Some(Ok(()));
+                }
+            }
+            DirEvent::Failed(e) => progress.finished = Some(Err(e)),
+        }
+    }
+
+    fn abandoned(&self) -> io::Error {
+        io::Error::other("directory listing was abandoned")
+    }
+}
+
+impl Drop for DirLoad {
+    fn drop(&mut self) {
+        self.job.cancel();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::fs::LocalFsBackend;
+    use tempfile::TempDir;
+
+    fn start_load(runtime: &tokio::runtime::Runtime, pool: &WorkerPool, path: &Path) -> DirLoad {
+        let manager = Arc::new(FsManager::new(Arc::new(LocalFsBackend::new())));
+        DirLoad::start(pool, runtime.handle().clone(), manager, path, None)
+    }
+
+    fn load_all(load: &mut DirLoad) -> (Vec<FsEntry>, io::Result<()>) {
+        let mut entries = Vec::new();
+        loop {
+            let progress = load.wait(Duration::from_secs(10));
+            entries.extend(progress.entries);
+            if let Some(result) = progress.finished {
+                return (entries, result);
+            }
+        }
+    }
+
+    #[test]
+    fn test_lists_directory_in_batches() {
+        let temp_dir = TempDir::new().unwrap();
+        for i in 0..DIR_BATCH_SIZE + 20 {
+            std::fs::write(temp_dir.path().join(format!("f{i}")), "x").unwrap();
+        }
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let pool = WorkerPool::new(1, None);
+        let mut load = start_load(&runtime, &pool, temp_dir.path());
+
+        let first = load.wait(Duration::from_secs(10));
+        assert_eq!(first.entries.len(), DIR_BATCH_SIZE);
+        // Nothing is stat'ed up front
+        assert!(first.entries.iter().all(|entry| entry.metadata.is_none()));
+
+        let (rest, result) = load_all(&mut load);
+        result.unwrap();
+        assert_eq!(rest.len(), 20);
+        assert_eq!(load.loaded(), DIR_BATCH_SIZE + 20);
+    }
+
+    #[test]
+    fn test_empty_directory_finishes() {
+        let temp_dir = TempDir::new().unwrap();
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let pool = WorkerPool::new(1, None);
+        let mut load = start_load(&runtime, &pool, temp_dir.path());
+
+        let (entries, result) = load_all(&mut load);
+        result.unwrap();
+        assert!(entries.is_empty());
+    }
+
+    #[test]
+    fn test_missing_directory_fails() {
+        let temp_dir = TempDir::new().unwrap();
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let pool = WorkerPool::new(1, None);
+        let mut load = start_load(&runtime, &pool, &temp_dir.path().join("missing"));
+
+        let (_, result) = load_all(&mut load);
+        assert!(result.is_err());
+    }
+}
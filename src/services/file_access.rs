@@ -0,0 +1,107 @@
+//! Whether open files can be written
+//!
+//! Checked without writing anything, so asking doesn't show up as a change
+//! to the file watcher: `access(2)` on Unix, and the read-only attribute plus
+//! an attempt to open the file for writing on Windows.
+
+use std::io;
+use std::path::Path;
+
+/// Why a file can't be written
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReadOnlyReason {
+    /// The file's permissions (or ACL, or read-only attribute) deny writing
+    Permissions,
+    /// The file is on a file system mounted read-only
+    ReadOnlyFilesystem,
+}
+
+impl ReadOnlyReason {
+    /// Name of the reason as plugins see it
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Permissions => "permissions",
+            Self::ReadOnlyFilesystem => "read_only_filesystem",
+        }
+    }
+}
+
+/// Why `path` can't be written, or None if it can
+///
+/// Files that don't exist (yet) and anything that isn't a regular file
+/// count as writable; saving reports their errors.
+pub fn read_only_reason(path: &Path) -> Option<ReadOnlyReason> {
+    match std::fs::metadata(path) {
+        Ok(metadata) if metadata.is_file() => {}
+        _ => return None,
+    }
+    write_access_error(path).and_then(|e| reason_for_error(&e))
+}
+
+/// Why a write failed with `error`, if it failed for lack of write access
+pub fn reason_for_error(error: &io::Error) -> Option<ReadOnlyReason> {
+    #[cfg(unix)]
+    if error.raw_os_error() == Some(libc::EROFS) {
+        return Some(ReadOnlyReason::ReadOnlyFilesystem);
+    }
+    match error.kind() {
+        io::ErrorKind::PermissionDenied => Some(ReadOnlyReason::Permissions),
+        _ => None,
+    }
+}
+
+#[cfg(unix)]
+fn write_access_error(path: &Path) -> Option<io::Error> {
+    use std::os::unix::ffi::OsStrExt;
+    let c_path = std::ffi::CString::new(path.as_os_str().as_bytes()).ok()?;
+    // SAFETY: c_path is a valid NUL-terminated string for the call's duration
+    if unsafe { libc::access(c_path.as_ptr(), libc::W_OK) } == 0 {
+        None
+    } else {
+        Some(io::Error::last_os_error())
+    }
+}
+
+#[cfg(windows)]
+fn write_access_error(path: &Path) -> Option<io::Error> {
+    // The read-only attribute, then whatever the ACL says
+    let read_only = std::fs::metadata(path).is_ok_and(|m| m.permissions().readonly());
+    if read_only {
+        return Some(io::Error::from(io::ErrorKind::PermissionDenied));
+    }
+    std::fs::OpenOptions::new().write(true).open(path).err()
+}
+
+#[cfg(not(any(unix, windows)))]
+fn write_access_error(path: &Path) -> Option<io::Error> {
+    let read_only = std::fs::metadata(path).is_ok_and(|m| m.permissions().readonly());
+    read_only.then(|| io::Error::from(io::ErrorKind::PermissionDenied))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_missing_file_is_writable() {
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(read_only_reason(&dir.path().join("missing.txt")), None);
+    }
+
+    #[test]
+    fn test_permission_denied_error() {
+        let error = io::Error::from(io::ErrorKind::PermissionDenied);
+        assert_eq!(reason_for_error(&error), Some(ReadOnlyReason::Permissions));
+        assert_eq!(reason_for_error(&io::Error::other("disk full")), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_read_only_filesystem_error() {
+        let error = io::Error::from_raw_os_error(libc::EROFS);
+        assert_eq!(
+            reason_for_error(&error),
+            Some(ReadOnlyReason::ReadOnlyFilesystem)
+        );
+    }
+}
@@ -0,0 +1,296 @@
+//! Background loading of file contents
+//!
+//! Reading a file on a slow mount (sshfs, NFS, a network drive) used to block
+//! the main loop until the whole file had arrived. A [`FileLoad`] reads the
+//! file in chunks on the worker pool instead and reports progress as it goes;
+//! the editor shows a placeholder buffer until the contents are complete.
+//!
+//! Reads go through a [`FileSource`] so tests can substitute a slow or failing
+//! reader for the local file system.
+
+use crate::services::async_bridge::AsyncMessage;
+use crate::services::worker_pool::{CancellationToken, JobHandle, JobPriority, WorkerPool};
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::sync::{mpsc, Arc};
+use std::time::{Duration, Instant};
+
+/// Number of bytes read between progress reports
+pub const LOAD_CHUNK_SIZE: usize = 256 * 1024;
+
+/// Where file contents are read from
+pub trait FileSource: Send + Sync {
+    /// Size of the file in bytes
+    fn size(&self, path: &Path) -> io::Result<u64>;
+
+    /// Open the file for sequential reading
+    fn open(&self, path: &Path) -> io::Result<Box<dyn Read + Send>>;
+}
+
+/// Reads files from the local file system
+#[derive(Debug, Default, Clone, Copy)]
+pub struct LocalFileSource;
+
+impl FileSource for LocalFileSource {
+    fn size(&self, path: &Path) -> io::Result<u64> {
+        Ok(std::fs::metadata(path)?.len())
+    }
+
+    fn open(&self, path: &Path) -> io::Result<Box<dyn Read + Send>> {
+        Ok(Box::new(std::fs::File::open(path)?))
+    }
+}
+
+enum LoadEvent {
+    Progress(u64),
+    Finished(io::Result<Vec<u8>>),
+}
+
+/// A file being read in the background
+///
+/// Dropping the load cancels the read.
+pub struct FileLoad {
+    path: PathBuf,
+    total_bytes: u64,
+    loaded_bytes: u64,
+    receiver: mpsc::Receiver<LoadEvent>,
+    job: JobHandle,
+}
+
+impl FileLoad {
+    /// Start reading `path` on the worker pool
+    ///
+    /// `total_bytes` is the expected size, used only for progress reporting.
+    /// If `wake` is given, an [`AsyncMessage::FileLoadProgress`] is sent after
+    /// every chunk so the main loop polls the load promptly.
+    pub fn start(
+        pool: &WorkerPool,
+        source: Arc<dyn FileSource>,
+        path: &Path,
+        total_bytes: u64,
+        wake: Option<mpsc::Sender<AsyncMessage>>,
+    ) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        let job_path = path.to_path_buf();
+
+        let job = pool.submit("file-load", JobPriority::UserBlocking, move |token| {
+            let notify = || {
+                if let Some(wake) = &wake {
+                    let _ = wake.send(AsyncMessage::FileLoadProgress {
+                        path: job_path.clone(),
+                    });
+                }
+            };
+            let result = read_chunks(&*source, &job_path, total_bytes, token, |loaded| {
+                let _ = sender.send(LoadEvent::Progress(loaded));
+                notify();
+            });
+            if token.is_cancelled() {
+                return None;
+            }
+            let _ = sender.send(LoadEvent::Finished(result));
+            notify();
+            None
+        });
+
+        Self {
+            path: path.to_path_buf(),
+            total_bytes,
+            loaded_bytes: 0,
+            receiver,
+            job,
+        }
+    }
+
+    /// Path being read
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Bytes read so far and the expected total
+    pub fn progress(&self) -> (u64, u64) {
+        (self.loaded_bytes, self.total_bytes)
+    }
+
+    /// Progress as a percentage (0-100)
+    pub fn percent(&self) -> u8 {
+        if self.total_bytes == 0 {
+            return 100;
+        }
+        (self.loaded_bytes.saturating_mul(100) / self.total_bytes).min(100) as u8
+    }
+
+    /// Collect progress without blocking; returns the result once the read ends
+    pub fn poll(&mut self) -> Option<io::Result<Vec<u8>>> {
+        loop {
+            match self.receiver.try_recv() {
+                Ok(event) => {
+                    if let Some(result) = self.handle(event) {
+                        return Some(result);
+                    }
+                }
+                Err(mpsc::TryRecvError::Empty) => return None,
+                Err(mpsc::TryRecvError::Disconnected) => return Some(Err(self.abandoned())),
+            }
+        }
+    }
+
+    /// Block for up to `timeout` waiting for the read to end
+    pub fn wait(&mut self, timeout: Duration) -> Option<io::Result<Vec<u8>>> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            match self.receiver.recv_timeout(remaining) {
+                Ok(event) => {
+                    if let Some(result) = self.handle(event) {
+                        return Some(result);
+                    }
+                }
+                Err(mpsc::RecvTimeoutError::Timeout) => return None,
+                Err(mpsc::RecvTimeoutError::Disconnected) => return Some(Err(self.abandoned())),
+            }
+        }
+    }
+
+    fn handle(&mut self, event: LoadEvent) -> Option<io::Result<Vec<u8>>> {
+        match event {
+            LoadEvent::Progress(loaded) => {
+                self.loaded_bytes = loaded;
+                None
+            }
+            LoadEvent::Finished(result) => {
+                if let Ok(bytes) = &result {
+                    self.loaded_bytes = bytes.len() as u64;
+                }
+                Some(result)
+            }
+        }
+    }
+
+    fn abandoned(&self) -> io::Error {
+        io::Error::other("file load was abandoned")
+    }
+}
+
+impl Drop for FileLoad {
+    fn drop(&mut self) {
+        self.job.cancel();
+    }
+}
+
+/// Read a whole file, reporting the byte count after each chunk
+fn read_chunks(
+    source: &dyn FileSource,
+    path: &Path,
+    total_bytes: u64,
+    token: &CancellationToken,
+    mut on_progress: impl FnMut(u64),
+) -> io::Result<Vec<u8>> {
+    let mut reader = source.open(path)?;
+    let mut contents = Vec::with_capacity(total_bytes as usize);
+    let mut chunk = vec![0u8; LOAD_CHUNK_SIZE];
+
+    loop {
+        if token.is_cancelled() {
+            return Err(io::Error::new(io::ErrorKind::Interrupted, "load cancelled"));
+        }
+        let mut filled = 0;
+        while filled < chunk.len() {
+            match reader.read(&mut chunk[filled..]) {
+                Ok(0) => break,
+                Ok(n) => filled += n,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            }
+        }
+        contents.extend_from_slice(&chunk[..filled]);
+        if filled < chunk.len() {
+            return Ok(contents);
+        }
+        on_progress(contents.len() as u64);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    struct MemorySource(Vec<u8>);
+
+    impl FileSource for MemorySource {
+        fn size(&self, _path: &Path) -> io::Result<u64> {
+            Ok(self.0.len() as u64)
+        }
+
+        fn open(&self, _path: &Path) -> io::Result<Box<dyn Read + Send>> {
+            Ok(Box::new(Cursor::new(self.0.clone())))
+        }
+    }
+
+    /// Fails after returning the first `good` bytes
+    struct FailingReader {
+        good: usize,
+    }
+
+    impl Read for FailingReader {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            if self.good == 0 {
+                return Err(io::Error::other("connection reset"));
+            }
+            let n = self.good.min(buf.len());
+            buf[..n].fill(b'x');
+            self.good -= n;
+            Ok(n)
+        }
+    }
+
+    struct FailingSource;
+
+    impl FileSource for FailingSource {
+        fn size(&self, _path: &Path) -> io::Result<u64> {
+            Ok(10)
+        }
+
+        fn open(&self, _path: &Path) -> io::Result<Box<dyn Read + Send>> {
+            Ok(Box::new(FailingReader { good: 4 }))
+        }
+    }
+
+    #[test]
+    fn test_load_reads_all_chunks() {
+        let pool = WorkerPool::new(1, None);
+        let data: Vec<u8> = (0..LOAD_CHUNK_SIZE * 2 + 17).map(|i| i as u8).collect();
+        let source = Arc::new(MemorySource(data.clone()));
+        let mut load = FileLoad::start(&pool, source, Path::new("a"), data.len() as u64, None);
+
+        let result = load.wait(Duration::from_secs(10)).expect("load finished");
+        assert_eq!(result.unwrap(), data);
+        assert_eq!(load.percent(), 100);
+    }
+
+    #[test]
+    fn test_load_reports_read_error() {
+        let pool = WorkerPool::new(1, None);
+        let mut load = FileLoad::start(&pool, Arc::new(FailingSource), Path::new("a"), 10, None);
+
+        let result = load.wait(Duration::from_secs(10)).expect("load finished");
+        assert_eq!(result.unwrap_err().to_string(), "connection reset");
+    }
+
+    #[test]
+    fn test_empty_file_is_complete() {
+        let pool = WorkerPool::new(1, None);
+        let mut load = FileLoad::start(
+            &pool,
+            Arc::new(MemorySource(Vec::new())),
+            Path::new("a"),
+            0,
+            None,
+        );
+
+        let result = load.wait(Duration::from_secs(10)).expect("load finished");
+        assert!(result.unwrap().is_empty());
+        assert_eq!(load.percent(), 100);
+    }
+}
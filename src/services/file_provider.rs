@@ -0,0 +1,172 @@
+//! Where files live
+//!
+//! Files are read, written and listed through a [`FileProvider`]. Local
+//! files go through [`LocalFileProvider`]; paths written as URLs, such as
+//! `ssh://host/etc/hosts`, go through the provider registered for their
+//! scheme (see [`crate::services::remote`]). Other ways of reaching files,
+//! such as `docker exec`, only need to implement the trait.
+
+use crate::services::file_loader::{FileSource, LocalFileSource};
+use crate::services::file_saver::{FileSink, LocalFileSink};
+use crate::services::fs::{FsEntry, FsEntryType};
+use crate::services::lsp::uri::uri_scheme;
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+
+/// Reads, writes and lists files in one place
+pub trait FileProvider: FileSource + FileSink {
+    /// What is at `path`, or None if nothing is
+    fn entry_type(&self, path: &Path) -> io::Result<Option<FsEntryType>>;
+
+    /// Entries of the directory at `path`, in no particular order
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<FsEntry>>;
+}
+
+/// Files on the local file system
+#[derive(Debug, Default, Clone, Copy)]
+pub struct LocalFileProvider;
+
+impl FileSource for LocalFileProvider {
+    fn size(&self, path: &Path) -> io::Result<u64> {
+        LocalFileSource.size(path)
+    }
+
+    fn open(&self, path: &Path) -> io::Result<Box<dyn Read + Send>> {
+        LocalFileSource.open(path)
+    }
+}
+
+impl FileSink for LocalFileProvider {
+    fn replace(
+        &self,
+        path: &Path,
+        contents: &mut dyn FnMut(&mut dyn Write) -> io::Result<()>,
+    ) -> io::Result<u64> {
+        LocalFileSink.replace(path, contents)
+    }
+}
+
+impl FileProvider for LocalFileProvider {
+    fn entry_type(&self, path: &Path) -> io::Result<Option<FsEntryType>> {
+        match std::fs::metadata(path) {
+            Ok(metadata) if metadata.is_dir() => Ok(Some(FsEntryType::Directory)),
+            Ok(_) => Ok(Some(FsEntryType::File)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<FsEntry>> {
+        let mut entries = Vec::new();
+        for entry in std::fs::read_dir(path)? {
+            let entry = entry?;
+            let entry_type = if entry.path().is_dir() {
+                FsEntryType::Directory
+            } else {
+                FsEntryType::File
+            };
+            entries.push(FsEntry::new(
+                entry.path(),
+                entry.file_name().to_string_lossy().into_owned(),
+                entry_type,
+            ));
+        }
+        Ok(entries)
+    }
+}
+
+/// Scheme of a path written as a URL, such as `ssh` for `ssh://host/path`
+///
+/// `file://` URIs name local files, so they have none.
+pub fn remote_scheme(path: &Path) -> Option<&str> {
+    let text = path.to_str()?;
+    let scheme = uri_scheme(text)?;
+    let rest = &text[scheme.len() + 1..];
+    (scheme != "file" && rest.starts_with("//")).then_some(scheme)
+}
+
+/// Whether `path` names a file reached through a provider other than the
+/// local file system
+pub fn is_remote_path(path: &Path) -> bool {
+    remote_scheme(path).is_some()
+}
+
+/// The directory containing a remote path, or None at the root of its host
+pub fn remote_parent(path: &Path) -> Option<PathBuf> {
+    let scheme = remote_scheme(path)?;
+    let text = path.to_str()?;
+    let authority = scheme.len() + "://".len();
+    let root = authority + text[authority..].find('/')?;
+    let trimmed = text.trim_end_matches('/');
+    if trimmed.len() <= root {
+        return None;
+    }
+    let slash = trimmed.rfind('/')?;
+    Some(PathBuf::from(&trimmed[..slash.max(root + 1)]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_remote_scheme() {
+        assert_eq!(
+            remote_scheme(Path::new("ssh://host/etc/hosts")),
+            Some("ssh")
+        );
+        assert_eq!(
+            remote_scheme(Path::new("docker+exec://app/x")),
+            Some("docker+exec")
+        );
+        assert_eq!(remote_scheme(Path::new("file:///etc/hosts")), None);
+        assert_eq!(remote_scheme(Path::new("/etc/hosts")), None);
+        assert_eq!(remote_scheme(Path::new("notes:12")), None);
+        assert_eq!(remote_scheme(Path::new("C://src")), None);
+    }
+
+    #[test]
+    fn test_remote_parent() {
+        let parent = |path: &str| remote_parent(Path::new(path)).map(|p| p.display().to_string());
+        assert_eq!(
+            parent("ssh://host/etc/ssh"),
+            Some("ssh://host/etc".to_string())
+        );
+        assert_eq!(parent("ssh://host/etc/"), Some("ssh://host/".to_string()));
+        assert_eq!(parent("ssh://host/"), None);
+        assert_eq!(parent("ssh://host"), None);
+        assert_eq!(parent("/etc/ssh"), None);
+    }
+
+    #[test]
+    fn test_local_provider_lists_and_identifies_entries() {
+        let temp_dir = TempDir::new().unwrap();
+        std::fs::write(temp_dir.path().join("a.txt"), "a").unwrap();
+        std::fs::create_dir(temp_dir.path().join("sub")).unwrap();
+        let provider = LocalFileProvider;
+
+        let mut entries = provider.read_dir(temp_dir.path()).unwrap();
+        entries.sort_by(|a, b| a.name.cmp(&b.name));
+        let listed: Vec<_> = entries
+            .iter()
+            .map(|e| (e.name.as_str(), e.is_dir()))
+            .collect();
+        assert_eq!(listed, vec![("a.txt", false), ("sub", true)]);
+
+        assert_eq!(
+            provider.entry_type(&temp_dir.path().join("sub")).unwrap(),
+            Some(FsEntryType::Directory)
+        );
+        assert_eq!(
+            provider.entry_type(&temp_dir.path().join("a.txt")).unwrap(),
+            Some(FsEntryType::File)
+        );
+        assert_eq!(
+            provider
+                .entry_type(&temp_dir.path().join("missing"))
+                .unwrap(),
+            None
+        );
+    }
+}
@@ -0,0 +1,384 @@
+//! Background writing of buffer contents
+//!
+//! Saving a huge buffer used to block the main loop for the whole write. A
+//! [`FileSave`] writes a [`SaveSnapshot`] of the buffer on the worker pool
+//! instead, so the buffer can be edited while its file is written.
+//!
+//! Writes go through a [`FileSink`] so tests can substitute a slow or failing
+//! writer for the local file system.
+
+use crate::model::buffer::{replace_file, SaveSnapshot};
+use crate::services::async_bridge::AsyncMessage;
+use crate::services::worker_pool::{JobPriority, WorkerPool};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{mpsc, Arc};
+use std::time::Duration;
+
+/// Number of bytes written between progress reports
+pub const SAVE_PROGRESS_INTERVAL: u64 = 1024 * 1024;
+
+/// Where file contents are written to
+pub trait FileSink: Send + Sync {
+    /// Replace the file at `path` with what `contents` writes
+    ///
+    /// Returns the size of the new file.
+    fn replace(
+        &self,
+        path: &Path,
+        contents: &mut dyn FnMut(&mut dyn Write) -> io::Result<()>,
+    ) -> io::Result<u64>;
+}
+
+/// Writes files to the local file system, through a temporary file
+#[derive(Debug, Default, Clone, Copy)]
+pub struct LocalFileSink;
+
+impl FileSink for LocalFileSink {
+    fn replace(
+        &self,
+        path: &Path,
+        contents: &mut dyn FnMut(&mut dyn Write) -> io::Result<()>,
+    ) -> io::Result<u64> {
+        replace_file(path, contents).map(|size| size as u64)
+    }
+}
+
+/// Writes files through a command, such as `sudo -n tee`, that gets the
+/// contents on its standard input and the path as its last argument
+///
+/// Used to save files the editor itself may not write. The file is written
+/// in place, so it keeps its owner and permissions.
+#[derive(Debug, Clone)]
+pub struct CommandFileSink {
+    command: Vec<String>,
+}
+
+impl CommandFileSink {
+    pub fn new(command: Vec<String>) -> Self {
+        Self { command }
+    }
+}
+
+impl FileSink for CommandFileSink {
+    fn replace(
+        &self,
+        path: &Path,
+        contents: &mut dyn FnMut(&mut dyn Write) -> io::Result<()>,
+    ) -> io::Result<u64> {
+        let (program, args) = self
+            .command
+            .split_first()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "no save command"))?;
+        let mut command = Command::new(program);
+        command.args(args).arg(path);
+        pipe_to_command(command, contents)
+    }
+}
+
+/// Run `command` with what `contents` writes on its standard input
+///
+/// Returns the number of bytes written. A command that exits unsuccessfully
+/// fails with its error output.
+pub(crate) fn pipe_to_command(
+    mut command: Command,
+    contents: &mut dyn FnMut(&mut dyn Write) -> io::Result<()>,
+) -> io::Result<u64> {
+    let mut child = command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()?;
+
+    let mut size = 0;
+    let written = match child.stdin.take() {
+        Some(mut stdin) => contents(&mut SizeWriter {
+            inner: &mut stdin,
+            size: &mut size,
+        }),
+        None => Ok(()),
+    };
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        return Err(command_error(
+            command.get_program(),
+            &output.stderr,
+            output.status,
+        ));
+    }
+    written?;
+    Ok(size)
+}
+
+/// Error for a command that exited with `status`, preferring its error output
+pub(crate) fn command_error(
+    program: &std::ffi::OsStr,
+    stderr: &[u8],
+    status: std::process::ExitStatus,
+) -> io::Error {
+    let stderr = String::from_utf8_lossy(stderr).trim().to_string();
+    io::Error::other(if stderr.is_empty() {
+        format!("{} failed ({})", program.to_string_lossy(), status)
+    } else {
+        stderr
+    })
+}
+
+/// Counts the bytes written through it
+struct SizeWriter<'a> {
+    inner: &'a mut dyn Write,
+    size: &'a mut u64,
+}
+
+impl Write for SizeWriter<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = self.inner.write(buf)?;
+        *self.size += n as u64;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// A snapshot being written in the background
+///
+/// A save is never cancelled once started: dropping it only stops listening
+/// for the result, and the file is still written.
+pub struct FileSave {
+    path: PathBuf,
+    snapshot: Arc<SaveSnapshot>,
+    written: Arc<AtomicU64>,
+    receiver: mpsc::Receiver<io::Result<u64>>,
+}
+
+impl FileSave {
+    /// Start writing `snapshot` to `path` on the worker pool
+    ///
+    /// If `wake` is given, an [`AsyncMessage::FileSaveProgress`] is sent as
+    /// the write progresses and when it ends, so the main loop polls the save
+    /// promptly.
+    pub fn start(
+        pool: &WorkerPool,
+        sink: Arc<dyn FileSink>,
+        path: &Path,
+        snapshot: SaveSnapshot,
+        wake: Option<mpsc::Sender<AsyncMessage>>,
+    ) -> Self {
+        let written = Arc::new(AtomicU64::new(0));
+        let counter = Arc::clone(&written);
+        let job_path = path.to_path_buf();
+        let progress_wake = wake.clone();
+        Self::spawn(pool, path, snapshot, wake, written, move |snapshot| {
+            sink.replace(&job_path, &mut |out| {
+                let mut out = CountingWriter {
+                    inner: out,
+                    written: &counter,
+                    path: &job_path,
+                    wake: progress_wake.as_ref(),
+                };
+                snapshot.write_to(&mut out)
+            })
+        })
+    }
+
+    /// Run `write` with `snapshot` on the worker pool, tracked like a save
+    /// of `path`
+    ///
+    /// Used for writes of a buffer's contents that don't replace its file,
+    /// such as recovery snapshots.
+    pub fn start_with(
+        pool: &WorkerPool,
+        path: &Path,
+        snapshot: SaveSnapshot,
+        wake: Option<mpsc::Sender<AsyncMessage>>,
+        write: impl FnOnce(&SaveSnapshot) -> io::Result<u64> + Send + 'static,
+    ) -> Self {
+        let written = Arc::new(AtomicU64::new(0));
+        Self::spawn(pool, path, snapshot, wake, written, write)
+    }
+
+    fn spawn(
+        pool: &WorkerPool,
+        path: &Path,
+        snapshot: SaveSnapshot,
+        wake: Option<mpsc::Sender<AsyncMessage>>,
+        written: Arc<AtomicU64>,
+        write: impl FnOnce(&SaveSnapshot) -> io::Result<u64> + Send + 'static,
+    ) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        let snapshot = Arc::new(snapshot);
+        let job_snapshot = Arc::clone(&snapshot);
+        let job_path = path.to_path_buf();
+
+        pool.submit("file-save", JobPriority::UserBlocking, move |_token| {
+            let _ = sender.send(write(&job_snapshot));
+            if let Some(wake) = &wake {
+                let _ = wake.send(AsyncMessage::FileSaveProgress { path: job_path });
+            }
+            None
+        });
+
+        Self {
+            path: path.to_path_buf(),
+            snapshot,
+            written,
+            receiver,
+        }
+    }
+
+    /// Path being written
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The contents being written
+    pub fn snapshot(&self) -> &SaveSnapshot {
+        &self.snapshot
+    }
+
+    /// Progress as a percentage (0-100)
+    pub fn percent(&self) -> u8 {
+        let total = self.snapshot.len() as u64;
+        if total == 0 {
+            return 100;
+        }
+        let written = self.written.load(Ordering::Relaxed);
+        (written.saturating_mul(100) / total).min(100) as u8
+    }
+
+    /// Check without blocking whether the write ended; returns the size of
+    /// the written file once it has
+    pub fn poll(&mut self) -> Option<io::Result<u64>> {
+        match self.receiver.try_recv() {
+            Ok(result) => Some(result),
+            Err(mpsc::TryRecvError::Empty) => None,
+            Err(mpsc::TryRecvError::Disconnected) => Some(Err(abandoned())),
+        }
+    }
+
+    /// Block for up to `timeout` waiting for the write to end
+    pub fn wait(&mut self, timeout: Duration) -> Option<io::Result<u64>> {
+        match self.receiver.recv_timeout(timeout) {
+            Ok(result) => Some(result),
+            Err(mpsc::RecvTimeoutError::Timeout) => None,
+            Err(mpsc::RecvTimeoutError::Disconnected) => Some(Err(abandoned())),
+        }
+    }
+}
+
+fn abandoned() -> io::Error {
+    io::Error::other("file save was abandoned")
+}
+
+/// Counts the bytes written, waking the main loop every
+/// [`SAVE_PROGRESS_INTERVAL`] bytes
+struct CountingWriter<'a> {
+    inner: &'a mut dyn Write,
+    written: &'a AtomicU64,
+    path: &'a Path,
+    wake: Option<&'a mpsc::Sender<AsyncMessage>>,
+}
+
+impl Write for CountingWriter<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = self.inner.write(buf)?;
+        let before = self.written.fetch_add(n as u64, Ordering::Relaxed);
+        if (before + n as u64) / SAVE_PROGRESS_INTERVAL > before / SAVE_PROGRESS_INTERVAL {
+            if let Some(wake) = self.wake {
+                let _ = wake.send(AsyncMessage::FileSaveProgress {
+                    path: self.path.to_path_buf(),
+                });
+            }
+        }
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::buffer::TextBuffer;
+    use tempfile::TempDir;
+
+    struct FailingSink;
+
+    impl FileSink for FailingSink {
+        fn replace(
+            &self,
+            _path: &Path,
+            contents: &mut dyn FnMut(&mut dyn Write) -> io::Result<()>,
+        ) -> io::Result<u64> {
+            contents(&mut io::sink())?;
+            Err(io::Error::other("disk full"))
+        }
+    }
+
+    #[test]
+    fn test_save_writes_snapshot() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("a.txt");
+        let pool = WorkerPool::new(1, None);
+        let mut buffer = TextBuffer::from_bytes(b"hello\n".to_vec());
+        let snapshot = buffer.save_snapshot().unwrap();
+
+        // Edits after the snapshot don't reach the file
+        buffer.insert_bytes(0, b"more ".to_vec());
+        let mut save = FileSave::start(&pool, Arc::new(LocalFileSink), &path, snapshot, None);
+
+        let size = save.wait(Duration::from_secs(10)).expect("save finished");
+        assert_eq!(size.unwrap(), 6);
+        assert_eq!(std::fs::read(&path).unwrap(), b"hello\n");
+        assert_eq!(save.percent(), 100);
+    }
+
+    #[test]
+    fn test_save_reports_write_error() {
+        let pool = WorkerPool::new(1, None);
+        let buffer = TextBuffer::from_bytes(b"hello\n".to_vec());
+        let snapshot = buffer.save_snapshot().unwrap();
+        let mut save =
+            FileSave::start(&pool, Arc::new(FailingSink), Path::new("a"), snapshot, None);
+
+        let result = save.wait(Duration::from_secs(10)).expect("save finished");
+        assert_eq!(result.unwrap_err().to_string(), "disk full");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_command_sink_writes_through_command() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("a.txt");
+        let pool = WorkerPool::new(1, None);
+        let buffer = TextBuffer::from_bytes(b"hello\n".to_vec());
+        let snapshot = buffer.save_snapshot().unwrap();
+        let sink = CommandFileSink::new(vec!["tee".to_string()]);
+        let mut save = FileSave::start(&pool, Arc::new(sink), &path, snapshot, None);
+
+        let size = save.wait(Duration::from_secs(10)).expect("save finished");
+        assert_eq!(size.unwrap(), 6);
+        assert_eq!(std::fs::read(&path).unwrap(), b"hello\n");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_command_sink_reports_command_error() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("missing").join("a.txt");
+        let pool = WorkerPool::new(1, None);
+        let buffer = TextBuffer::from_bytes(b"hello\n".to_vec());
+        let snapshot = buffer.save_snapshot().unwrap();
+        let sink = CommandFileSink::new(vec!["tee".to_string()]);
+        let mut save = FileSave::start(&pool, Arc::new(sink), &path, snapshot, None);
+
+        let result = save.wait(Duration::from_secs(10)).expect("save finished");
+        assert!(result.unwrap_err().to_string().contains("a.txt"));
+    }
+}
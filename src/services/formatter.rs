@@ -0,0 +1,381 @@
+//! Running external formatters
+//!
+//! A [`FormatJob`] runs a language's formatter on the worker pool and hands
+//! back the formatted text. In stdin mode the text is piped through the
+//! formatter and its output is the result; in file mode the formatter
+//! rewrites a file in place and the result is the file afterwards. Either
+//! way the output only counts if the formatter exits successfully within its
+//! timeout.
+
+use crate::services::async_bridge::AsyncMessage;
+use crate::services::worker_pool::{CancellationToken, JobHandle, JobPriority, WorkerPool};
+use std::fmt;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How often a running formatter is checked for exit, timeout and cancellation
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// A formatter command line, ready to run
+#[derive(Debug, Clone)]
+pub struct FormatCommand {
+    /// The command, run by the shell (e.g. `rustfmt` or `npx prettier`)
+    pub command: String,
+    /// Arguments, passed as they are
+    pub args: Vec<String>,
+    /// Directory to run it in
+    pub working_dir: PathBuf,
+    pub timeout: Duration,
+}
+
+impl FormatCommand {
+    /// `command` with `args`, whose `{file}` and `$FILE` placeholders
+    /// become `file`
+    pub fn new(command: &str, args: &[String], file: &Path, working_dir: &Path) -> Self {
+        let file = file.to_string_lossy();
+        Self {
+            command: command.to_string(),
+            args: args
+                .iter()
+                .map(|arg| arg.replace("{file}", &file).replace("$FILE", &file))
+                .collect(),
+            working_dir: working_dir.to_path_buf(),
+            timeout: Duration::from_millis(10_000),
+        }
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    fn to_command(&self) -> Command {
+        #[cfg(windows)]
+        let mut command = {
+            let mut command = Command::new("cmd");
+            command.arg("/C").arg(&self.command);
+            command
+        };
+        // The shell resolves the command; arguments are passed as they are,
+        // so paths with spaces survive
+        #[cfg(not(windows))]
+        let mut command = {
+            let mut command = Command::new("sh");
+            command
+                .arg("-c")
+                .arg(format!("{} \"$@\"", self.command))
+                .arg("sh");
+            command
+        };
+        command.args(&self.args).current_dir(&self.working_dir);
+        command
+    }
+}
+
+/// What the formatter works on
+#[derive(Debug, Clone)]
+pub enum FormatInput {
+    /// Text piped to its standard input; its output replaces it
+    Stdin(String),
+    /// A file it rewrites in place
+    File(PathBuf),
+}
+
+/// Why formatting produced no text
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FormatError {
+    /// The command doesn't exist
+    NotFound(String),
+    /// The formatter exited unsuccessfully, with its error output
+    Failed {
+        command: String,
+        output: String,
+    },
+    /// The formatter ran longer than its timeout and was stopped
+    TimedOut {
+        command: String,
+        timeout_ms: u64,
+    },
+    /// Running it or reading its result failed
+    Io {
+        command: String,
+        error: String,
+    },
+    Cancelled,
+}
+
+impl fmt::Display for FormatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotFound(command) => write!(f, "Formatter '{}' not found", command),
+            Self::Failed { command, output } => {
+                write!(f, "Formatter '{}' failed: {}", command, output)
+            }
+            Self::TimedOut {
+                command,
+                timeout_ms,
+            } => write!(
+                f,
+                "Formatter '{}' timed out after {}ms",
+                command, timeout_ms
+            ),
+            Self::Io { command, error } => {
+                write!(f, "Failed to run formatter '{}': {}", command, error)
+            }
+            Self::Cancelled => write!(f, "Formatting was cancelled"),
+        }
+    }
+}
+
+/// Run a formatter and return the formatted text
+///
+/// The formatter is killed once `token` is cancelled or its timeout passes.
+pub fn run_formatter(
+    command: &FormatCommand,
+    input: &FormatInput,
+    token: &CancellationToken,
+) -> Result<String, FormatError> {
+    let io_error = |e: std::io::Error| FormatError::Io {
+        command: command.command.clone(),
+        error: e.to_string(),
+    };
+
+    let stdin = match input {
+        FormatInput::Stdin(_) => Stdio::piped(),
+        FormatInput::File(_) => Stdio::null(),
+    };
+    let mut child = command
+        .to_command()
+        .stdin(stdin)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => FormatError::NotFound(command.command.clone()),
+            _ => io_error(e),
+        })?;
+
+    // Feed and drain the pipes on their own threads, so a formatter that
+    // writes before reading all of its input can't block on a full pipe
+    if let (FormatInput::Stdin(text), Some(mut pipe)) = (input, child.stdin.take()) {
+        let text = text.clone();
+        thread::spawn(move || {
+            let _ = pipe.write_all(text.as_bytes());
+        });
+    }
+    let stdout = read_pipe(child.stdout.take());
+    let stderr = read_pipe(child.stderr.take());
+
+    let status = wait_for_exit(&mut child, command, token)?;
+    let stdout = stdout.join().unwrap_or_default();
+    let stderr = stderr.join().unwrap_or_default();
+
+    if !status.success() {
+        // The shell's code for a command it couldn't find
+        #[cfg(unix)]
+        if status.code() == Some(127) {
+            return Err(FormatError::NotFound(command.command.clone()));
+        }
+        let stderr = String::from_utf8_lossy(&stderr);
+        let stdout = String::from_utf8_lossy(&stdout);
+        let output = if !stderr.trim().is_empty() {
+            stderr.trim().to_string()
+        } else if !stdout.trim().is_empty() {
+            stdout.trim().to_string()
+        } else {
+            format!("exit code {:?}", status.code())
+        };
+        return Err(FormatError::Failed {
+            command: command.command.clone(),
+            output,
+        });
+    }
+
+    match input {
+        FormatInput::Stdin(_) => String::from_utf8(stdout).map_err(|e| FormatError::Io {
+            command: command.command.clone(),
+            error: format!("Invalid UTF-8 in output: {}", e),
+        }),
+        FormatInput::File(path) => std::fs::read_to_string(path).map_err(io_error),
+    }
+}
+
+fn read_pipe(pipe: Option<impl Read + Send + 'static>) -> thread::JoinHandle<Vec<u8>> {
+    thread::spawn(move || {
+        let mut bytes = Vec::new();
+        if let Some(mut pipe) = pipe {
+            let _ = pipe.read_to_end(&mut bytes);
+        }
+        bytes
+    })
+}
+
+fn wait_for_exit(
+    child: &mut Child,
+    command: &FormatCommand,
+    token: &CancellationToken,
+) -> Result<std::process::ExitStatus, FormatError> {
+    let start = Instant::now();
+    loop {
+        match child.try_wait() {
+            Ok(Some(status)) => return Ok(status),
+            Ok(None) => {}
+            Err(e) => {
+                return Err(FormatError::Io {
+                    command: command.command.clone(),
+                    error: e.to_string(),
+                })
+            }
+        }
+        if token.is_cancelled() {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(FormatError::Cancelled);
+        }
+        if start.elapsed() > command.timeout {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(FormatError::TimedOut {
+                command: command.command.clone(),
+                timeout_ms: command.timeout.as_millis() as u64,
+            });
+        }
+        thread::sleep(POLL_INTERVAL);
+    }
+}
+
+/// A formatter running on the worker pool
+pub struct FormatJob {
+    handle: JobHandle,
+    receiver: mpsc::Receiver<Result<String, FormatError>>,
+}
+
+impl FormatJob {
+    /// Start running `command` on `input`
+    ///
+    /// An [`AsyncMessage::FormatFinished`] for `path` wakes the main loop
+    /// when it ends.
+    pub fn start(
+        pool: &WorkerPool,
+        command: FormatCommand,
+        input: FormatInput,
+        path: &Path,
+    ) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        let path = path.to_path_buf();
+        let handle = pool.submit("format", JobPriority::UserBlocking, move |token| {
+            let _ = sender.send(run_formatter(&command, &input, token));
+            Some(AsyncMessage::FormatFinished { path })
+        });
+        Self { handle, receiver }
+    }
+
+    /// The result, if the formatter has finished
+    pub fn poll(&mut self) -> Option<Result<String, FormatError>> {
+        match self.receiver.try_recv() {
+            Ok(result) => Some(result),
+            Err(mpsc::TryRecvError::Empty) => None,
+            Err(mpsc::TryRecvError::Disconnected) => Some(Err(FormatError::Cancelled)),
+        }
+    }
+
+    /// Block for up to `timeout` waiting for the result
+    pub fn wait(&mut self, timeout: Duration) -> Option<Result<String, FormatError>> {
+        match self.receiver.recv_timeout(timeout) {
+            Ok(result) => Some(result),
+            Err(mpsc::RecvTimeoutError::Timeout) => None,
+            Err(mpsc::RecvTimeoutError::Disconnected) => Some(Err(FormatError::Cancelled)),
+        }
+    }
+
+    /// Stop the formatter
+    pub fn cancel(&self) {
+        self.handle.cancel();
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn command(command: &str, args: &[&str], dir: &Path) -> FormatCommand {
+        let args: Vec<String> = args.iter().map(|a| a.to_string()).collect();
+        FormatCommand::new(command, &args, &dir.join("a b.txt"), dir)
+    }
+
+    fn run(command: &FormatCommand, input: &FormatInput) -> Result<String, FormatError> {
+        run_formatter(command, input, &CancellationToken::new())
+    }
+
+    #[test]
+    fn test_placeholders_become_the_file() {
+        let dir = Path::new("/project");
+        let formatter = command("prettier", &["--stdin-filepath", "{file}", "$FILE"], dir);
+        assert_eq!(
+            formatter.args,
+            vec!["--stdin-filepath", "/project/a b.txt", "/project/a b.txt"]
+        );
+    }
+
+    #[test]
+    fn test_stdin_mode_returns_output() {
+        let temp_dir = TempDir::new().unwrap();
+        let formatter = command("tr", &["a-z", "A-Z"], temp_dir.path());
+        let input = FormatInput::Stdin("hello\n".to_string());
+        assert_eq!(run(&formatter, &input).unwrap(), "HELLO\n");
+    }
+
+    #[test]
+    fn test_file_mode_returns_rewritten_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let file = temp_dir.path().join("a b.txt");
+        std::fs::write(&file, "hello\n").unwrap();
+        let formatter = command(
+            "f() { tr a-z A-Z < \"$1\" > \"$1.out\" && mv \"$1.out\" \"$1\"; }; f",
+            &["{file}"],
+            temp_dir.path(),
+        );
+        let input = FormatInput::File(file.clone());
+        assert_eq!(run(&formatter, &input).unwrap(), "HELLO\n");
+    }
+
+    #[test]
+    fn test_failure_reports_error_output() {
+        let temp_dir = TempDir::new().unwrap();
+        let formatter = command("echo 'line 3: bad' >&2; exit 2; :", &[], temp_dir.path());
+        let input = FormatInput::Stdin("text".to_string());
+        assert_eq!(
+            run(&formatter, &input),
+            Err(FormatError::Failed {
+                command: formatter.command.clone(),
+                output: "line 3: bad".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn test_missing_command_and_timeout() {
+        let temp_dir = TempDir::new().unwrap();
+        let missing = command("fresh_no_such_formatter", &[], temp_dir.path());
+        let input = FormatInput::Stdin(String::new());
+        assert_eq!(
+            run(&missing, &input),
+            Err(FormatError::NotFound("fresh_no_such_formatter".to_string()))
+        );
+
+        let slow =
+            command("sleep", &["5"], temp_dir.path()).with_timeout(Duration::from_millis(50));
+        assert_eq!(
+            run(&slow, &input),
+            Err(FormatError::TimedOut {
+                command: "sleep".to_string(),
+                timeout_ms: 50,
+            })
+        );
+    }
+}
@@ -0,0 +1,392 @@
+//! `git blame` for the inline blame annotations
+//!
+//! [`start_blame`] runs `git blame --porcelain` for one file on the worker
+//! pool and reports the parsed [`Blame`] in an
+//! [`AsyncMessage::GitBlameFinished`]. The result is tagged with the file's
+//! git blob hash: a request that already holds a blame for the same blob only
+//! runs `git hash-object` and hands the cached blame back.
+//!
+//! The blamed text is rebuilt from the porcelain output, so lines edited since
+//! (saved or not) are found by diffing it against the buffer with
+//! [`map_lines`] rather than by running git again.
+
+use crate::model::line_diff::matching_lines;
+use crate::services::async_bridge::AsyncMessage;
+use crate::services::worker_pool::{JobHandle, JobPriority, WorkerPool};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+use std::sync::Arc;
+
+/// A commit that lines of the file are attributed to
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BlameCommit {
+    /// Full commit hash; all zeros for lines that aren't committed
+    pub hash: String,
+    pub author: String,
+    /// Author time (Unix epoch seconds)
+    pub author_time: i64,
+    /// First line of the commit message
+    pub summary: String,
+}
+
+impl BlameCommit {
+    /// Whether this stands for lines not committed yet
+    pub fn is_uncommitted(&self) -> bool {
+        self.hash.bytes().all(|b| b == b'0')
+    }
+
+    /// Abbreviated hash, as shown by `git log --oneline`
+    pub fn short_hash(&self) -> &str {
+        &self.hash[..self.hash.len().min(8)]
+    }
+}
+
+/// Parsed `git blame --porcelain` output
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Blame {
+    pub commits: Vec<BlameCommit>,
+    /// Commit (index into `commits`) of each line of the blamed text
+    lines: Vec<Option<usize>>,
+    /// The blamed text, each line followed by `\n`
+    pub text: String,
+}
+
+impl Blame {
+    /// Commit of a 0-based line of the blamed text
+    pub fn commit_of_line(&self, line: usize) -> Option<&BlameCommit> {
+        let commit = self.lines.get(line).copied().flatten()?;
+        self.commits.get(commit)
+    }
+
+    /// Number of lines in the blamed text
+    pub fn line_count(&self) -> usize {
+        self.lines.len()
+    }
+}
+
+/// Parse the output of `git blame --porcelain`
+///
+/// Each group starts with `<hash> <original line> <final line> [<count>]`.
+/// The first group of a commit is followed by its `author`, `author-time`,
+/// `summary` and other headers; every group ends with the line's content
+/// after a tab. Unknown headers and malformed groups are skipped.
+pub fn parse_porcelain(output: &str) -> Blame {
+    let mut commits: Vec<BlameCommit> = Vec::new();
+    let mut by_hash: HashMap<&str, usize> = HashMap::new();
+    let mut lines: Vec<Option<usize>> = Vec::new();
+    let mut contents: Vec<&str> = Vec::new();
+    // Commit and 0-based final line of the group being read
+    let mut group: Option<(usize, usize)> = None;
+
+    for line in output.split('\n') {
+        if let Some(content) = line.strip_prefix('\t') {
+            if let Some((commit, final_line)) = group.take() {
+                if lines.len() <= final_line {
+                    lines.resize(final_line + 1, None);
+                    contents.resize(final_line + 1, "");
+                }
+                lines[final_line] = Some(commit);
+                contents[final_line] = content;
+            }
+            continue;
+        }
+
+        let Some((commit, _)) = group else {
+            let mut fields = line.split(' ');
+            let (Some(hash), Some(_), Some(final_line)) =
+                (fields.next(), fields.next(), fields.next())
+            else {
+                continue;
+            };
+            let Some(final_line) = final_line
+                .parse::<usize>()
+                .ok()
+                .and_then(|n| n.checked_sub(1))
+            else {
+                continue;
+            };
+            if hash.len() != 40 || !hash.bytes().all(|b| b.is_ascii_hexdigit()) {
+                continue;
+            }
+            let commit = *by_hash.entry(hash).or_insert_with(|| {
+                commits.push(BlameCommit {
+                    hash: hash.to_string(),
+                    ..Default::default()
+                });
+                commits.len() - 1
+            });
+            group = Some((commit, final_line));
+            continue;
+        };
+
+        let (key, value) = line.split_once(' ').unwrap_or((line, ""));
+        let commit = &mut commits[commit];
+        match key {
+            "author" => commit.author = value.to_string(),
+            "author-time" => commit.author_time = value.parse().unwrap_or_default(),
+            "summary" => commit.summary = value.to_string(),
+            _ => {}
+        }
+    }
+
+    let mut text = String::new();
+    for content in contents {
+        text.push_str(content);
+        text.push('\n');
+    }
+    Blame {
+        commits,
+        lines,
+        text,
+    }
+}
+
+/// Line of the blamed text each line of `current` comes from
+///
+/// Lines that were added or changed since the blame map to `None`; they are
+/// not committed yet.
+pub fn map_lines(blamed: &str, current: &[u8]) -> Vec<Option<usize>> {
+    let line_count = current.iter().filter(|&&b| b == b'\n').count() + 1;
+    let mut map = vec![None; line_count];
+    for (old, new) in matching_lines(blamed.as_bytes(), current) {
+        map[new] = Some(old);
+    }
+    map
+}
+
+/// How long ago a commit was made, in the largest whole unit
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Age {
+    JustNow,
+    Minutes(i64),
+    Hours(i64),
+    Days(i64),
+    Weeks(i64),
+    Months(i64),
+    Years(i64),
+}
+
+/// Age of something made at `then`, at `now` (both Unix epoch seconds)
+pub fn age(then: i64, now: i64) -> Age {
+    const MINUTE: i64 = 60;
+    const HOUR: i64 = 60 * MINUTE;
+    const DAY: i64 = 24 * HOUR;
+    const MONTH: i64 = 30 * DAY;
+    const YEAR: i64 = 365 * DAY;
+
+    let secs = now.saturating_sub(then);
+    if secs < MINUTE {
+        Age::JustNow
+    } else if secs < HOUR {
+        Age::Minutes(secs / MINUTE)
+    } else if secs < DAY {
+        Age::Hours(secs / HOUR)
+    } else if secs < 7 * DAY {
+        Age::Days(secs / DAY)
+    } else if secs < MONTH {
+        Age::Weeks(secs / (7 * DAY))
+    } else if secs < YEAR {
+        Age::Months(secs / MONTH)
+    } else {
+        Age::Years(secs / YEAR)
+    }
+}
+
+/// A blame together with the blob hash of the file it was made for
+#[derive(Debug, Clone)]
+pub struct BlameSnapshot {
+    pub blob: String,
+    pub blame: Arc<Blame>,
+}
+
+/// Why a blame couldn't be made
+#[derive(Debug, Clone, PartialEq)]
+pub enum BlameError {
+    /// The file isn't in a git repository or isn't tracked
+    NotInGit,
+    /// git failed; holds its message
+    Failed(String),
+}
+
+/// A request to blame one file
+pub struct BlameRequest {
+    pub request_id: u64,
+    pub path: PathBuf,
+    /// Blame made earlier for this file, reused if the blob is unchanged
+    pub cached: Option<BlameSnapshot>,
+}
+
+/// Blame a file on the worker pool
+///
+/// The result arrives as [`AsyncMessage::GitBlameFinished`] (nothing is sent
+/// if the job is cancelled).
+pub fn start_blame(pool: &WorkerPool, request: BlameRequest) -> JobHandle {
+    pool.submit("git-blame", JobPriority::UserBlocking, move |token| {
+        let result = blame_file(&request.path, request.cached);
+        if token.is_cancelled() {
+            return None;
+        }
+        Some(AsyncMessage::GitBlameFinished {
+            request_id: request.request_id,
+            path: request.path,
+            result,
+        })
+    })
+}
+
+/// Show the full message of a commit on the worker pool
+///
+/// The result arrives as [`AsyncMessage::GitCommitShown`].
+pub fn start_show_commit(pool: &WorkerPool, path: &Path, hash: String) -> JobHandle {
+    let dir = path.parent().map(Path::to_path_buf).unwrap_or_default();
+    pool.submit("git-show", JobPriority::UserBlocking, move |token| {
+        let result = run_git(
+            &dir,
+            &["show", "--no-patch", "--no-color", "--format=fuller", &hash],
+        )
+        .and_then(|output| {
+            if output.status.success() {
+                Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+            } else {
+                Err(git_error(&output))
+            }
+        });
+        if token.is_cancelled() {
+            return None;
+        }
+        Some(AsyncMessage::GitCommitShown { hash, result })
+    })
+}
+
+/// Run `git hash-object` and, unless `cached` is for the same blob, `git blame`
+fn blame_file(path: &Path, cached: Option<BlameSnapshot>) -> Result<BlameSnapshot, BlameError> {
+    let (Some(dir), Some(name)) = (path.parent(), path.file_name()) else {
+        return Err(BlameError::NotInGit);
+    };
+    let name = name.to_string_lossy();
+
+    let output = run_git(dir, &["hash-object", "--", &name]).map_err(BlameError::Failed)?;
+    if !output.status.success() {
+        return Err(BlameError::Failed(git_error(&output)));
+    }
+    let blob = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    if let Some(cached) = cached.filter(|cached| cached.blob == blob) {
+        return Ok(cached);
+    }
+
+    let output =
+        run_git(dir, &["blame", "--porcelain", "--", &name]).map_err(BlameError::Failed)?;
+    if !output.status.success() {
+        let message = git_error(&output);
+        if message.contains("not a git repository") || message.contains("no such path") {
+            return Err(BlameError::NotInGit);
+        }
+        return Err(BlameError::Failed(message));
+    }
+    let blame = parse_porcelain(&String::from_utf8_lossy(&output.stdout));
+    Ok(BlameSnapshot {
+        blob,
+        blame: Arc::new(blame),
+    })
+}
+
+fn run_git(dir: &Path, args: &[&str]) -> Result<Output, String> {
+    Command::new("git")
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .map_err(|e| e.to_string())
+}
+
+/// First line of git's error output
+fn git_error(output: &Output) -> String {
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    stderr
+        .lines()
+        .next()
+        .unwrap_or_default()
+        .trim_start_matches("fatal: ")
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PORCELAIN: &str = include_str!("../../tests/fixtures/git_blame/porcelain.txt");
+
+    #[test]
+    fn test_parse_porcelain_attributes_every_line() {
+        let blame = parse_porcelain(PORCELAIN);
+        assert_eq!(blame.line_count(), 5);
+        assert_eq!(blame.commits.len(), 3);
+
+        let authors: Vec<_> = (0..5)
+            .map(|line| blame.commit_of_line(line).unwrap().author.as_str())
+            .collect();
+        assert_eq!(
+            authors,
+            vec![
+                "Alice Example",
+                "Alice Example",
+                "Not Committed Yet",
+                "Bob Example",
+                "Alice Example"
+            ]
+        );
+
+        let first = blame.commit_of_line(0).unwrap();
+        assert_eq!(first.author_time, 1_700_000_000);
+        assert_eq!(first.summary, "Add greeting");
+        assert_eq!(first.short_hash(), "1f2e3d4c");
+        assert!(!first.is_uncommitted());
+        assert!(blame.commit_of_line(2).unwrap().is_uncommitted());
+        assert_eq!(blame.commit_of_line(3).unwrap().summary, "Shout louder");
+        assert!(blame.commit_of_line(5).is_none());
+
+        assert_eq!(
+            blame.text,
+            "fn main() {\n    let name = \"world\";\n    // TODO: ask for a name\n    println!(\"HELLO, {}!\", name);\n}\n"
+        );
+    }
+
+    #[test]
+    fn test_parse_porcelain_skips_garbage() {
+        let blame = parse_porcelain("not porcelain\n\tstray content\n");
+        assert_eq!(blame, Blame::default());
+    }
+
+    #[test]
+    fn test_map_lines_marks_edited_lines() {
+        let blame = parse_porcelain(PORCELAIN);
+        let current = "fn main() {\n    let name = \"world\";\n    let greeting = \"hi\";\n    // TODO: ask for a name\n    println!(\"hi, {}!\", name);\n}\n";
+        let map = map_lines(&blame.text, current.as_bytes());
+        assert_eq!(
+            map,
+            vec![Some(0), Some(1), None, Some(2), None, Some(4), Some(5)]
+        );
+
+        let commit = |line: usize| map[line].and_then(|line| blame.commit_of_line(line));
+        assert_eq!(commit(1).unwrap().author, "Alice Example");
+        // Inserted and changed lines are not committed
+        assert!(commit(2).is_none());
+        assert!(commit(4).is_none());
+        // A line git reports as uncommitted stays that way
+        assert!(commit(3).unwrap().is_uncommitted());
+    }
+
+    #[test]
+    fn test_age() {
+        let now = 1_700_000_000;
+        assert_eq!(age(now - 30, now), Age::JustNow);
+        assert_eq!(age(now - 5 * 60, now), Age::Minutes(5));
+        assert_eq!(age(now - 3 * 3600, now), Age::Hours(3));
+        assert_eq!(age(now - 2 * 86400, now), Age::Days(2));
+        assert_eq!(age(now - 15 * 86400, now), Age::Weeks(2));
+        assert_eq!(age(now - 90 * 86400, now), Age::Months(3));
+        assert_eq!(age(now - 800 * 86400, now), Age::Years(2));
+        // Clock skew doesn't produce negative ages
+        assert_eq!(age(now + 100, now), Age::JustNow);
+    }
+}
@@ -0,0 +1,544 @@
+//! `.gitattributes` lookup for the attributes that affect how files are saved
+//!
+//! Only `eol` and `working-tree-encoding` are resolved. Attribute files are
+//! read from the file's directory up to the root of its git work tree, plus
+//! the repository's `info/attributes`, and matched the way git does it:
+//!
+//! - Within one file, later lines override earlier ones.
+//! - A `.gitattributes` closer to the file overrides the ones above it, and
+//!   `info/attributes` overrides them all.
+//! - A pattern without a slash matches the file name at any depth below its
+//!   `.gitattributes`; one with a slash matches the path relative to it.
+//! - Patterns ending in a slash only match directories, so never a file,
+//!   and negative patterns are not allowed.
+//!
+//! Parsed files are cached per directory. The cache is not checked on every
+//! lookup; [`GitAttributes::invalidate_stale`] is called from the file
+//! change polling to drop files that were edited, created or removed.
+
+use crate::model::buffer::LineEnding;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::SystemTime;
+
+/// Name of the per-directory attributes file
+const ATTRIBUTES_FILE: &str = ".gitattributes";
+
+/// The save conventions `.gitattributes` asks for
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FileAttributes {
+    /// Line ending from `eol=lf` or `eol=crlf`
+    pub eol: Option<LineEnding>,
+    /// Encoding name from `working-tree-encoding`
+    pub working_tree_encoding: Option<String>,
+}
+
+impl FileAttributes {
+    fn from_values(values: &HashMap<String, AttrValue>) -> Self {
+        // Neither attribute applies to files that are not text
+        if values.get("text") == Some(&AttrValue::Unset) {
+            return Self::default();
+        }
+        let value = |name: &str| match values.get(name) {
+            Some(AttrValue::Value(value)) => Some(value.as_str()),
+            _ => None,
+        };
+        Self {
+            eol: match value("eol") {
+                Some("lf") => Some(LineEnding::LF),
+                Some("crlf") => Some(LineEnding::CRLF),
+                _ => None,
+            },
+            working_tree_encoding: value("working-tree-encoding").map(str::to_string),
+        }
+    }
+}
+
+/// What one line does to an attribute
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum AttrValue {
+    /// `attr`
+    Set,
+    /// `-attr`
+    Unset,
+    /// `!attr`, back to no value
+    Unspecified,
+    /// `attr=value`
+    Value(String),
+}
+
+/// One pattern line of an attributes file
+#[derive(Debug, Clone)]
+struct Rule {
+    /// Glob without its leading and trailing slash
+    pattern: String,
+    /// Match the file name rather than the path (no slash in the pattern)
+    basename_only: bool,
+    /// Pattern ended with a slash and only matches directories
+    dir_only: bool,
+    attrs: Vec<(String, AttrValue)>,
+}
+
+impl Rule {
+    /// Whether the rule applies to a file at `relative` (slash-separated,
+    /// relative to the attributes file's directory)
+    fn matches(&self, relative: &str) -> bool {
+        if self.dir_only {
+            return false;
+        }
+        let subject = if self.basename_only {
+            relative.rsplit('/').next().unwrap_or(relative)
+        } else {
+            relative
+        };
+        wildmatch(self.pattern.as_bytes(), subject.as_bytes())
+    }
+}
+
+/// Parse the lines of an attributes file
+fn parse(contents: &str) -> Vec<Rule> {
+    let mut rules = Vec::new();
+    for line in contents.lines() {
+        let mut fields = line.split_whitespace();
+        let Some(pattern) = fields.next() else {
+            continue;
+        };
+        // Comments, macro definitions and negative patterns (which git
+        // rejects) don't assign attributes to files
+        if pattern.starts_with('#') || pattern.starts_with("[attr]") || pattern.starts_with('!') {
+            continue;
+        }
+
+        let mut attrs = Vec::new();
+        for field in fields {
+            let (name, value) = if let Some(name) = field.strip_prefix('-') {
+                (name, AttrValue::Unset)
+            } else if let Some(name) = field.strip_prefix('!') {
+                (name, AttrValue::Unspecified)
+            } else if let Some((name, value)) = field.split_once('=') {
+                (name, AttrValue::Value(value.to_string()))
+            } else {
+                (field, AttrValue::Set)
+            };
+            // The built-in `binary` macro
+            if name == "binary" && value == AttrValue::Set {
+                for implied in ["diff", "merge", "text"] {
+                    attrs.push((implied.to_string(), AttrValue::Unset));
+                }
+            }
+            attrs.push((name.to_string(), value));
+        }
+
+        let dir_only = pattern.ends_with('/');
+        let pattern = pattern.trim_end_matches('/');
+        rules.push(Rule {
+            basename_only: !pattern.contains('/'),
+            pattern: pattern.strip_prefix('/').unwrap_or(pattern).to_string(),
+            dir_only,
+            attrs,
+        });
+    }
+    rules
+}
+
+/// Match a path against a git glob, where `*` and `?` don't match slashes
+/// and `**` matches any number of directories
+fn wildmatch(pattern: &[u8], text: &[u8]) -> bool {
+    matches_from(pattern, 0, text)
+}
+
+/// Match `text` against `pattern[index..]`; the whole pattern is kept to
+/// tell whether a `**` makes up an entire path component
+fn matches_from(pattern: &[u8], mut index: usize, mut text: &[u8]) -> bool {
+    while index < pattern.len() {
+        match pattern[index] {
+            b'*' => {
+                let mut end = index;
+                while end < pattern.len() && pattern[end] == b'*' {
+                    end += 1;
+                }
+                let whole_component = (index == 0 || pattern[index - 1] == b'/')
+                    && (end == pattern.len() || pattern[end] == b'/');
+                if end - index >= 2 && whole_component {
+                    if end == pattern.len() {
+                        return true;
+                    }
+                    // `**/` matches zero or more directories
+                    let next = end + 1;
+                    return matches_from(pattern, next, text)
+                        || text.iter().enumerate().any(|(i, &c)| {
+                            c == b'/' && matches_from(pattern, next, &text[i + 1..])
+                        });
+                }
+                let mut rest = text;
+                loop {
+                    if matches_from(pattern, end, rest) {
+                        return true;
+                    }
+                    match rest.split_first() {
+                        Some((&c, tail)) if c != b'/' => rest = tail,
+                        _ => return false,
+                    }
+                }
+            }
+            b'?' => match text.split_first() {
+                Some((&c, tail)) if c != b'/' => {
+                    text = tail;
+                    index += 1;
+                }
+                _ => return false,
+            },
+            b'[' => {
+                let Some((&c, tail)) = text.split_first() else {
+                    return false;
+                };
+                match match_bracket(pattern, index, c) {
+                    Some((matched, next)) => {
+                        if !matched || c == b'/' {
+                            return false;
+                        }
+                        index = next;
+                    }
+                    // An unclosed bracket is a literal
+                    None if c == b'[' => index += 1,
+                    None => return false,
+                }
+                text = tail;
+            }
+            literal => {
+                let literal = if literal == b'\\' && index + 1 < pattern.len() {
+                    index += 1;
+                    pattern[index]
+                } else {
+                    literal
+                };
+                match text.split_first() {
+                    Some((&c, tail)) if c == literal => {
+                        text = tail;
+                        index += 1;
+                    }
+                    _ => return false,
+                }
+            }
+        }
+    }
+    text.is_empty()
+}
+
+/// Match `c` against the bracket expression starting at `pattern[index]`
+///
+/// Returns whether it matched and where the expression ends, or None if the
+/// bracket is never closed.
+fn match_bracket(pattern: &[u8], index: usize, c: u8) -> Option<(bool, usize)> {
+    let mut i = index + 1;
+    let negated = matches!(pattern.get(i), Some(b'!' | b'^'));
+    if negated {
+        i += 1;
+    }
+    let mut matched = false;
+    let mut first = true;
+    loop {
+        let mut low = *pattern.get(i)?;
+        if low == b']' && !first {
+            return Some((matched != negated, i + 1));
+        }
+        first = false;
+        if low == b'\\' {
+            i += 1;
+            low = *pattern.get(i)?;
+        }
+        match (pattern.get(i + 1), pattern.get(i + 2)) {
+            (Some(b'-'), Some(&high)) if high != b']' => {
+                matched |= (low..=high).contains(&c);
+                i += 3;
+            }
+            _ => {
+                matched |= low == c;
+                i += 1;
+            }
+        }
+    }
+}
+
+/// The git work tree a file belongs to
+struct Repository {
+    work_tree: PathBuf,
+    /// `info/attributes` in the (common) git directory
+    info_attributes: PathBuf,
+}
+
+/// Find the work tree containing `dir`, following `.git` files of linked
+/// work trees and submodules to their git directory
+fn find_repository(dir: &Path) -> Option<Repository> {
+    for candidate in dir.ancestors() {
+        let dot_git = candidate.join(".git");
+        let git_dir = if dot_git.is_dir() {
+            dot_git
+        } else if dot_git.is_file() {
+            let contents = std::fs::read_to_string(&dot_git).ok()?;
+            let target = contents.trim().strip_prefix("gitdir:")?.trim();
+            candidate.join(target)
+        } else {
+            continue;
+        };
+        let common_dir = match std::fs::read_to_string(git_dir.join("commondir")) {
+            Ok(common) => git_dir.join(common.trim()),
+            Err(_) => git_dir,
+        };
+        return Some(Repository {
+            work_tree: candidate.to_path_buf(),
+            info_attributes: common_dir.join("info").join("attributes"),
+        });
+    }
+    None
+}
+
+/// Modification time and size of an attributes file, None if it is missing
+type Signature = Option<(Option<SystemTime>, u64)>;
+
+fn signature(path: &Path) -> Signature {
+    let metadata = std::fs::metadata(path).ok()?;
+    Some((metadata.modified().ok(), metadata.len()))
+}
+
+/// A parsed attributes file (empty if the file doesn't exist)
+struct CachedFile {
+    signature: Signature,
+    rules: Arc<Vec<Rule>>,
+}
+
+/// Attribute lookup with a per-directory cache of parsed attribute files
+#[derive(Default)]
+pub struct GitAttributes {
+    files: HashMap<PathBuf, CachedFile>,
+}
+
+impl GitAttributes {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Resolve the attributes for a file
+    ///
+    /// Files outside a git work tree get no attributes.
+    pub fn lookup(&mut self, path: &Path) -> FileAttributes {
+        let Some(dir) = path.parent() else {
+            return FileAttributes::default();
+        };
+        let Some(repo) = find_repository(dir) else {
+            return FileAttributes::default();
+        };
+
+        // Lowest precedence first, so later assignments win
+        let mut sources: Vec<(PathBuf, &Path)> = dir
+            .ancestors()
+            .take_while(|ancestor| ancestor.starts_with(&repo.work_tree))
+            .map(|ancestor| (ancestor.join(ATTRIBUTES_FILE), ancestor))
+            .collect();
+        sources.reverse();
+        sources.push((repo.info_attributes.clone(), repo.work_tree.as_path()));
+
+        let mut values = HashMap::new();
+        for (file, base) in sources {
+            let Ok(relative) = path.strip_prefix(base) else {
+                continue;
+            };
+            let relative = relative
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+            for rule in self.rules(&file).iter() {
+                if !rule.matches(&relative) {
+                    continue;
+                }
+                for (name, value) in &rule.attrs {
+                    if *value == AttrValue::Unspecified {
+                        values.remove(name);
+                    } else {
+                        values.insert(name.clone(), value.clone());
+                    }
+                }
+            }
+        }
+        FileAttributes::from_values(&values)
+    }
+
+    /// Drop cached files that changed on disk since they were read
+    ///
+    /// Returns how many were dropped.
+    pub fn invalidate_stale(&mut self) -> usize {
+        let before = self.files.len();
+        self.files
+            .retain(|path, cached| signature(path) == cached.signature);
+        before - self.files.len()
+    }
+
+    fn rules(&mut self, file: &Path) -> Arc<Vec<Rule>> {
+        let cached = self.files.entry(file.to_path_buf()).or_insert_with(|| {
+            let signature = signature(file);
+            let rules = match signature {
+                Some(_) => parse(&std::fs::read_to_string(file).unwrap_or_default()),
+                None => Vec::new(),
+            };
+            CachedFile {
+                signature,
+                rules: Arc::new(rules),
+            }
+        });
+        Arc::clone(&cached.rules)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    /// A work tree with a `.git` directory and the given attribute files
+    fn repo(files: &[(&str, &str)]) -> TempDir {
+        let dir = TempDir::new().unwrap();
+        fs::create_dir_all(dir.path().join(".git/info")).unwrap();
+        for (path, contents) in files {
+            let path = dir.path().join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, contents).unwrap();
+        }
+        dir
+    }
+
+    fn eol(attrs: &mut GitAttributes, dir: &TempDir, file: &str) -> Option<LineEnding> {
+        attrs.lookup(&dir.path().join(file)).eol
+    }
+
+    #[test]
+    fn test_later_lines_override_earlier() {
+        let dir = repo(&[(".gitattributes", "* text eol=lf\n*.bat eol=crlf\n")]);
+        let mut attrs = GitAttributes::new();
+        assert_eq!(eol(&mut attrs, &dir, "a.txt"), Some(LineEnding::LF));
+        assert_eq!(eol(&mut attrs, &dir, "run.bat"), Some(LineEnding::CRLF));
+
+        let dir = repo(&[(".gitattributes", "*.bat eol=crlf\n* text eol=lf\n")]);
+        assert_eq!(eol(&mut attrs, &dir, "run.bat"), Some(LineEnding::LF));
+    }
+
+    #[test]
+    fn test_closer_directories_override_farther() {
+        let dir = repo(&[
+            (".gitattributes", "*.bat eol=crlf\n"),
+            ("scripts/.gitattributes", "*.bat eol=lf\n"),
+            ("scripts/deep/.gitattributes", "# nothing here\n"),
+        ]);
+        let mut attrs = GitAttributes::new();
+        assert_eq!(eol(&mut attrs, &dir, "run.bat"), Some(LineEnding::CRLF));
+        assert_eq!(
+            eol(&mut attrs, &dir, "scripts/run.bat"),
+            Some(LineEnding::LF)
+        );
+        assert_eq!(
+            eol(&mut attrs, &dir, "scripts/deep/run.bat"),
+            Some(LineEnding::LF)
+        );
+    }
+
+    #[test]
+    fn test_info_attributes_override_everything() {
+        let dir = repo(&[
+            ("sub/.gitattributes", "*.txt eol=crlf\n"),
+            (".git/info/attributes", "*.txt !eol\n"),
+        ]);
+        let mut attrs = GitAttributes::new();
+        assert_eq!(eol(&mut attrs, &dir, "sub/a.txt"), None);
+    }
+
+    #[test]
+    fn test_files_outside_a_repository_are_unaffected() {
+        let dir = TempDir::new().unwrap();
+        fs::write(dir.path().join(".gitattributes"), "* eol=crlf\n").unwrap();
+        let mut attrs = GitAttributes::new();
+        assert_eq!(
+            attrs.lookup(&dir.path().join("a.txt")),
+            FileAttributes::default()
+        );
+    }
+
+    #[test]
+    fn test_binary_and_unset_text_disable_conversions() {
+        let dir = repo(&[(
+            ".gitattributes",
+            "* eol=crlf working-tree-encoding=UTF-16LE\n*.png binary\n*.dat -text\n",
+        )]);
+        let mut attrs = GitAttributes::new();
+        assert_eq!(
+            attrs.lookup(&dir.path().join("a.txt")),
+            FileAttributes {
+                eol: Some(LineEnding::CRLF),
+                working_tree_encoding: Some("UTF-16LE".to_string()),
+            }
+        );
+        assert_eq!(
+            attrs.lookup(&dir.path().join("a.png")),
+            FileAttributes::default()
+        );
+        assert_eq!(
+            attrs.lookup(&dir.path().join("a.dat")),
+            FileAttributes::default()
+        );
+    }
+
+    #[test]
+    fn test_patterns_with_slashes_are_relative_to_their_file() {
+        let dir = repo(&[
+            (
+                ".gitattributes",
+                "/top.txt eol=crlf\ndocs/**/*.md eol=crlf\nbuild/ eol=crlf\n",
+            ),
+            ("sub/.gitattributes", "nested/*.txt eol=crlf\n"),
+        ]);
+        let mut attrs = GitAttributes::new();
+        assert_eq!(eol(&mut attrs, &dir, "top.txt"), Some(LineEnding::CRLF));
+        assert_eq!(eol(&mut attrs, &dir, "sub/top.txt"), None);
+        assert_eq!(eol(&mut attrs, &dir, "docs/a.md"), Some(LineEnding::CRLF));
+        assert_eq!(
+            eol(&mut attrs, &dir, "docs/x/y/a.md"),
+            Some(LineEnding::CRLF)
+        );
+        assert_eq!(eol(&mut attrs, &dir, "build/out.txt"), None);
+        assert_eq!(
+            eol(&mut attrs, &dir, "sub/nested/a.txt"),
+            Some(LineEnding::CRLF)
+        );
+        assert_eq!(eol(&mut attrs, &dir, "nested/a.txt"), None);
+    }
+
+    #[test]
+    fn test_wildmatch() {
+        assert!(wildmatch(b"*.rs", b"main.rs"));
+        assert!(!wildmatch(b"*.rs", b"src/main.rs"));
+        assert!(wildmatch(b"**/main.rs", b"main.rs"));
+        assert!(wildmatch(b"**/main.rs", b"a/b/main.rs"));
+        assert!(wildmatch(b"src/**", b"src/a/b.rs"));
+        assert!(!wildmatch(b"src/**", b"src"));
+        assert!(wildmatch(b"a/**/b", b"a/b"));
+        assert!(wildmatch(b"a/**/b", b"a/x/y/b"));
+        assert!(wildmatch(b"file?.[ch]", b"file1.h"));
+        assert!(!wildmatch(b"file?.[!ch]", b"file1.h"));
+        assert!(wildmatch(b"[a-c]x", b"bx"));
+        assert!(!wildmatch(b"a?b", b"a/b"));
+        assert!(wildmatch(b"\\*lit", b"*lit"));
+    }
+
+    #[test]
+    fn test_invalidate_stale_rereads_changed_files() {
+        let dir = repo(&[]);
+        let mut attrs = GitAttributes::new();
+        assert_eq!(eol(&mut attrs, &dir, "a.txt"), None);
+        assert_eq!(attrs.invalidate_stale(), 0);
+
+        fs::write(dir.path().join(".gitattributes"), "* eol=crlf\n").unwrap();
+        assert_eq!(attrs.invalidate_stale(), 1);
+        assert_eq!(eol(&mut attrs, &dir, "a.txt"), Some(LineEnding::CRLF));
+    }
+}
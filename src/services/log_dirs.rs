@@ -4,6 +4,10 @@
 //! XDG Base Directory Specification. Logs are stored in:
 //! - `$XDG_STATE_HOME/fresh/logs/` (typically `~/.local/state/fresh/logs/`)
 //!
+//! `main` points this at the state directory of its
+//! [`DirectoryContext`](crate::config_io::DirectoryContext) with
+//! [`set_log_dir`], so portable installs keep their logs beside the binary.
+//!
 //! Each Fresh instance uses PID-based log files to support concurrent runs.
 //! On startup, stale log files from dead processes are cleaned up automatically.
 
@@ -38,6 +42,18 @@ pub fn log_dir() -> &'static PathBuf {
     })
 }
 
+/// Use `dir` as the log directory instead of the XDG default.
+///
+/// Must be called before the first log path is requested; later calls have
+/// no effect.
+pub fn set_log_dir(dir: PathBuf) {
+    if let Err(e) = fs::create_dir_all(&dir) {
+        tracing::warn!("Failed to create log directory {:?}: {}", dir, e);
+        return;
+    }
+    let _ = LOG_DIR.set(dir);
+}
+
 /// Get the XDG state home log directory
 fn get_xdg_log_dir() -> Option<PathBuf> {
     // First try XDG_STATE_HOME
@@ -177,7 +193,8 @@ fn cleanup_stale_logs_in_dir(dir: &std::path::Path, current_pid: u32) {
         let file_name = entry.file_name();
         let name = file_name.to_string_lossy();
 
-        // Only process .log files
+        // Only process .log files and their rotated copies
+        let name = without_rotation_suffix(&name);
         if !name.ends_with(".log") {
             continue;
         }
@@ -219,6 +236,21 @@ fn is_file_older_than(path: &std::path::Path, age: Duration) -> bool {
         .unwrap_or(false)
 }
 
+/// The name of a log without the `.N` suffix of a rotated copy, e.g.
+/// "fresh-12345.log" for "fresh-12345.log.2"
+fn without_rotation_suffix(name: &str) -> &str {
+    match name.rsplit_once('.') {
+        Some((base, index))
+            if base.ends_with(".log")
+                && !index.is_empty()
+                && index.chars().all(|c| c.is_ascii_digit()) =>
+        {
+            base
+        }
+        _ => name,
+    }
+}
+
 /// Extract PID from a filename like "fresh-12345.log" or "rust-12345.log"
 fn extract_pid_from_filename(name: &str) -> Option<u32> {
     // Remove .log extension
@@ -270,78 +302,88 @@ fn is_process_running(pid: u32) -> bool {
 }
 
 /// Print all directories used by Fresh to stdout
-pub fn print_all_paths() {
+pub fn print_all_paths(dir_context: &crate::config_io::DirectoryContext) {
     use std::io::Write;
 
     let stdout = std::io::stdout();
     let mut handle = stdout.lock();
 
-    // Config directory (~/.config/fresh)
-    let config_dir = dirs::config_dir()
-        .map(|d| d.join("fresh"))
-        .unwrap_or_else(|| PathBuf::from("<unavailable>"));
-
-    // Data directory (~/.local/share/fresh)
-    let data_dir = dirs::data_dir()
-        .map(|d| d.join("fresh"))
-        .unwrap_or_else(|| PathBuf::from("<unavailable>"));
-
-    // State/logs directory
+    let config_dir = &dir_context.config_dir;
+    let state_dir = &dir_context.state_dir;
     let logs_dir = log_dir().clone();
 
     writeln!(handle, "Fresh directories:").ok();
+    if dir_context.portable {
+        writeln!(handle, "(portable mode)").ok();
+    }
     writeln!(handle).ok();
 
     writeln!(handle, "Config:     {}", config_dir.display()).ok();
     writeln!(
         handle,
         "  config.json:  {}",
-        config_dir.join("config.json").display()
+        dir_context.config_path().display()
     )
     .ok();
     writeln!(
         handle,
         "  themes/:      {}",
-        config_dir.join("themes").display()
+        dir_context.themes_dir().display()
     )
     .ok();
     writeln!(
         handle,
         "  grammars/:    {}",
-        config_dir.join("grammars").display()
+        dir_context.grammars_dir().display()
     )
     .ok();
     writeln!(
         handle,
         "  plugins/:     {}",
-        config_dir.join("plugins").display()
+        dir_context.plugins_dir().display()
     )
     .ok();
     writeln!(handle).ok();
 
-    writeln!(handle, "Data:       {}", data_dir.display()).ok();
+    writeln!(handle, "State:      {}", state_dir.display()).ok();
     writeln!(
         handle,
         "  sessions/:    {}",
-        data_dir.join("sessions").display()
+        dir_context.sessions_dir().display()
     )
     .ok();
     writeln!(
         handle,
         "  recovery/:    {}",
-        data_dir.join("recovery").display()
+        dir_context.recovery_dir().display()
     )
     .ok();
     writeln!(
         handle,
         "  terminals/:   {}",
-        data_dir.join("terminals").display()
+        dir_context.terminals_dir().display()
     )
     .ok();
     writeln!(handle).ok();
 
+    writeln!(handle, "Cache:      {}", dir_context.cache_dir.display()).ok();
+    if let Some(runtime_dir) = &dir_context.runtime_dir {
+        writeln!(handle, "Runtime:    {}", runtime_dir.display()).ok();
+    }
+    writeln!(handle).ok();
+
     writeln!(handle, "Logs:       {}", logs_dir.display()).ok();
     writeln!(handle, "  lsp/:         {}", logs_dir.join("lsp").display()).ok();
+
+    if let Some(migration) = dir_context.pending_state_migration() {
+        writeln!(handle).ok();
+        writeln!(
+            handle,
+            "Old state not yet moved: {}",
+            migration.from.display()
+        )
+        .ok();
+    }
 }
 
 #[cfg(test)]
@@ -395,6 +437,20 @@ mod tests {
         assert_eq!(extract_pid_from_filename("invalid"), None);
     }
 
+    #[test]
+    fn test_without_rotation_suffix() {
+        assert_eq!(
+            without_rotation_suffix("fresh-12345.log.2"),
+            "fresh-12345.log"
+        );
+        assert_eq!(
+            without_rotation_suffix("fresh-12345.log"),
+            "fresh-12345.log"
+        );
+        assert_eq!(without_rotation_suffix("notes.txt.1"), "notes.txt.1");
+        assert_eq!(without_rotation_suffix("fresh.log.old"), "fresh.log.old");
+    }
+
     #[test]
     fn test_current_process_is_running() {
         assert!(is_process_running(std::process::id()));
@@ -14,15 +14,18 @@
 use crate::services::async_bridge::{
     AsyncBridge, AsyncMessage, LspMessageType, LspProgressValue, LspServerStatus,
 };
+use crate::services::lsp::document_sync::ServerSync;
+use crate::services::lsp::uri::uri_to_path;
 use crate::services::process_limits::ProcessLimits;
 use lsp_types::{
     notification::{
-        DidChangeTextDocument, DidOpenTextDocument, DidSaveTextDocument, Initialized, Notification,
-        PublishDiagnostics,
+        DidChangeTextDocument, DidCloseTextDocument, DidOpenTextDocument, DidSaveTextDocument,
+        Initialized, Notification, PublishDiagnostics,
     },
     request::{Initialize, Request, Shutdown},
-    ClientCapabilities, DidChangeTextDocumentParams, DidOpenTextDocumentParams,
-    DidSaveTextDocumentParams, InitializeParams, InitializeResult, InitializedParams,
+    ClientCapabilities, CodeActionProviderCapability, DidChangeTextDocumentParams,
+    DidCloseTextDocumentParams, DidOpenTextDocumentParams, DidSaveTextDocumentParams,
+    InitializeParams, InitializeResult, InitializedParams, PositionEncodingKind,
     PublishDiagnosticsParams, ServerCapabilities, TextDocumentContentChangeEvent,
     TextDocumentIdentifier, TextDocumentItem, Uri, VersionedTextDocumentIdentifier,
     WindowClientCapabilities, WorkspaceFolder,
@@ -31,9 +34,9 @@ use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::collections::HashMap;
 use std::path::PathBuf;
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::{mpsc as std_mpsc, Arc, Mutex};
-use std::time::Instant;
+use std::time::{Duration, Instant};
 use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
 use tokio::process::{Child, ChildStdin, ChildStdout, Command};
 use tokio::sync::{mpsc, oneshot};
@@ -42,6 +45,31 @@ use tokio::sync::{mpsc, oneshot};
 /// This gives the LSP server time to process didOpen before receiving changes
 const DID_OPEN_GRACE_PERIOD_MS: u64 = 200;
 
+/// How long to wait for the server to answer the `shutdown` request
+const SHUTDOWN_RESPONSE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// How long to wait for the server process to exit after `exit` before
+/// killing it
+const EXIT_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Upper bound on a graceful shutdown, from sending `shutdown` until the
+/// process is gone
+pub const GRACEFUL_SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(4);
+
+/// How often the server process is checked for being alive and responsive
+const HEALTH_CHECK_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Consecutive health checks a request may wait, with nothing at all heard
+/// from the server, before the server is reported unresponsive
+const UNRESPONSIVE_AFTER_CHECKS: u32 = 3;
+
+/// Path a document's versions are tracked under
+///
+/// Decoded, so a server spelling the URI differently still finds the document.
+fn document_path(uri: &Uri) -> PathBuf {
+    uri_to_path(uri.as_str()).unwrap_or_else(|_| PathBuf::from(uri.path().as_str()))
+}
+
 /// Check if a document is already open and should skip didOpen.
 /// Returns true if the document is already open (should skip), false if it should proceed.
 fn should_skip_did_open(
@@ -197,10 +225,12 @@ impl LspClientState {
 }
 
 /// Create common LSP client capabilities with workDoneProgress support
-fn create_client_capabilities() -> ClientCapabilities {
+fn create_client_capabilities(position_encodings: Vec<PositionEncodingKind>) -> ClientCapabilities {
     use lsp_types::{
-        GeneralClientCapabilities, RenameClientCapabilities, TextDocumentClientCapabilities,
-        WorkspaceClientCapabilities, WorkspaceEditClientCapabilities,
+        CodeActionClientCapabilities, CodeActionKind, CodeActionKindLiteralSupport,
+        CodeActionLiteralSupport, GeneralClientCapabilities, RenameClientCapabilities,
+        TextDocumentClientCapabilities, WorkspaceClientCapabilities,
+        WorkspaceEditClientCapabilities,
     };
 
     ClientCapabilities {
@@ -223,9 +253,32 @@ fn create_client_capabilities() -> ClientCapabilities {
                 honors_change_annotations: Some(true),
                 ..Default::default()
             }),
+            // Without literal support servers may only answer with commands
+            code_action: Some(CodeActionClientCapabilities {
+                code_action_literal_support: Some(CodeActionLiteralSupport {
+                    code_action_kind: CodeActionKindLiteralSupport {
+                        value_set: [
+                            CodeActionKind::EMPTY,
+                            CodeActionKind::QUICKFIX,
+                            CodeActionKind::REFACTOR,
+                            CodeActionKind::REFACTOR_EXTRACT,
+                            CodeActionKind::REFACTOR_INLINE,
+                            CodeActionKind::REFACTOR_REWRITE,
+                            CodeActionKind::SOURCE,
+                            CodeActionKind::SOURCE_ORGANIZE_IMPORTS,
+                            CodeActionKind::SOURCE_FIX_ALL,
+                        ]
+                        .into_iter()
+                        .map(|kind| kind.as_str().to_string())
+                        .collect(),
+                    },
+                }),
+                ..Default::default()
+            }),
             ..Default::default()
         }),
         general: Some(GeneralClientCapabilities {
+            position_encodings: Some(position_encodings),
             ..Default::default()
         }),
         // Enable rust-analyzer experimental features
@@ -236,6 +289,20 @@ fn create_client_capabilities() -> ClientCapabilities {
     }
 }
 
+/// Code action kinds a server's capabilities advertise (none if it only
+/// says it has code actions)
+fn code_action_kinds(capabilities: &ServerCapabilities) -> Vec<String> {
+    match &capabilities.code_action_provider {
+        Some(CodeActionProviderCapability::Options(options)) => options
+            .code_action_kinds
+            .iter()
+            .flatten()
+            .map(|kind| kind.as_str().to_string())
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
 /// Commands sent from the main loop to the LSP task
 #[derive(Debug)]
 enum LspCommand {
@@ -243,6 +310,8 @@ enum LspCommand {
     Initialize {
         root_uri: Option<Uri>,
         initialization_options: Option<Value>,
+        /// Position encodings to offer, preferred first
+        position_encodings: Vec<PositionEncodingKind>,
         response: oneshot::Sender<Result<InitializeResult, String>>,
     },
 
@@ -262,6 +331,9 @@ enum LspCommand {
     /// Notify document saved
     DidSave { uri: Uri, text: Option<String> },
 
+    /// Notify document closed
+    DidClose { uri: Uri },
+
     /// Request completion at position
     Completion {
         request_id: u64,
@@ -278,6 +350,14 @@ enum LspCommand {
         character: u32,
     },
 
+    /// Ask where a rename at a position would apply and what to pre-fill
+    PrepareRename {
+        request_id: u64,
+        uri: Uri,
+        line: u32,
+        character: u32,
+    },
+
     /// Request rename
     Rename {
         request_id: u64,
@@ -287,6 +367,17 @@ enum LspCommand {
         new_name: String,
     },
 
+    /// Request formatting edits after typing a character
+    OnTypeFormatting {
+        request_id: u64,
+        uri: Uri,
+        line: u32,
+        character: u32,
+        ch: char,
+        tab_size: u32,
+        insert_spaces: bool,
+    },
+
     /// Request hover documentation
     Hover {
         request_id: u64,
@@ -320,6 +411,8 @@ enum LspCommand {
         end_line: u32,
         end_char: u32,
         diagnostics: Vec<lsp_types::Diagnostic>,
+        /// Only return actions of these kinds
+        only: Option<Vec<lsp_types::CodeActionKind>>,
     },
 
     /// Request document diagnostics (pull model)
@@ -355,7 +448,10 @@ enum LspCommand {
     },
 
     /// Shutdown the server
-    Shutdown,
+    Shutdown {
+        /// Signalled once the server process is gone
+        done: Option<std_mpsc::Sender<()>>,
+    },
 }
 
 /// Mutable state for LSP command processing
@@ -429,6 +525,10 @@ impl LspState {
                     tracing::info!("Replaying DidSave for {}", uri.as_str());
                     let _ = self.handle_did_save(uri, text).await;
                 }
+                LspCommand::DidClose { uri } => {
+                    tracing::info!("Replaying DidClose for {}", uri.as_str());
+                    let _ = self.handle_did_close(uri).await;
+                }
                 _ => {}
             }
         }
@@ -542,6 +642,7 @@ impl LspState {
         &mut self,
         root_uri: Option<Uri>,
         initialization_options: Option<Value>,
+        position_encodings: Vec<PositionEncodingKind>,
         pending: &Arc<Mutex<HashMap<i64, oneshot::Sender<Result<Value, String>>>>>,
     ) -> Result<InitializeResult, String> {
         tracing::info!(
@@ -565,7 +666,7 @@ impl LspState {
 
         let params = InitializeParams {
             process_id: Some(std::process::id()),
-            capabilities: create_client_capabilities(),
+            capabilities: create_client_capabilities(position_encodings),
             workspace_folders,
             initialization_options,
             ..Default::default()
@@ -584,6 +685,23 @@ impl LspState {
         self.initialized = true;
 
         // Notify main loop
+        let _ = self.async_tx.send(AsyncMessage::LspCompletionTriggers {
+            language: self.language.clone(),
+            characters: result
+                .capabilities
+                .completion_provider
+                .as_ref()
+                .and_then(|provider| provider.trigger_characters.clone())
+                .unwrap_or_default(),
+        });
+        let _ = self.async_tx.send(AsyncMessage::LspCodeActionKinds {
+            language: self.language.clone(),
+            kinds: code_action_kinds(&result.capabilities),
+        });
+        let _ = self.async_tx.send(AsyncMessage::LspServerSync {
+            language: self.language.clone(),
+            sync: ServerSync::from_capabilities(&result.capabilities),
+        });
         let _ = self.async_tx.send(AsyncMessage::LspInitialized {
             language: self.language.clone(),
         });
@@ -607,7 +725,7 @@ impl LspState {
         language_id: String,
         _pending: &Arc<Mutex<HashMap<i64, oneshot::Sender<Result<Value, String>>>>>,
     ) -> Result<(), String> {
-        let path = PathBuf::from(uri.path().as_str());
+        let path = document_path(&uri);
 
         if should_skip_did_open(&self.document_versions, &path, &self.language, &uri) {
             return Ok(());
@@ -641,7 +759,7 @@ impl LspState {
     ) -> Result<(), String> {
         tracing::trace!("LSP: did_change for {}", uri.as_str());
 
-        let path = PathBuf::from(uri.path().as_str());
+        let path = document_path(&uri);
 
         // If the document hasn't been opened yet (not in document_versions),
         // skip this change - the upcoming didOpen will have the current content
@@ -699,6 +817,24 @@ impl LspState {
         self.send_notification::<DidSaveTextDocument>(params).await
     }
 
+    /// Handle did_close command
+    async fn handle_did_close(&mut self, uri: Uri) -> Result<(), String> {
+        tracing::trace!("LSP: did_close for {}", uri.as_str());
+
+        // Nothing to close if the server never got didOpen
+        let path = document_path(&uri);
+        if self.document_versions.remove(&path).is_none() {
+            return Ok(());
+        }
+        self.pending_opens.remove(&path);
+
+        let params = DidCloseTextDocumentParams {
+            text_document: TextDocumentIdentifier { uri },
+        };
+
+        self.send_notification::<DidCloseTextDocument>(params).await
+    }
+
     /// Handle completion request
     async fn handle_completion(
         &mut self,
@@ -851,6 +987,96 @@ impl LspState {
         }
     }
 
+    /// First document in a workspace edit whose version is not the one last sent
+    ///
+    /// Versioned edits only apply to the document version they were computed
+    /// against; unversioned edits and documents never opened are not checked.
+    fn stale_document_edit(&self, workspace_edit: &lsp_types::WorkspaceEdit) -> Option<PathBuf> {
+        use lsp_types::{DocumentChangeOperation, DocumentChanges};
+
+        let edits: Vec<&lsp_types::TextDocumentEdit> = match &workspace_edit.document_changes {
+            Some(DocumentChanges::Edits(edits)) => edits.iter().collect(),
+            Some(DocumentChanges::Operations(ops)) => ops
+                .iter()
+                .filter_map(|op| match op {
+                    DocumentChangeOperation::Edit(edit) => Some(edit),
+                    DocumentChangeOperation::Op(_) => None,
+                })
+                .collect(),
+            None => Vec::new(),
+        };
+        edits.into_iter().find_map(|edit| {
+            let version = edit.text_document.version?;
+            let path = document_path(&edit.text_document.uri);
+            let current = *self.document_versions.get(&path)?;
+            (current != i64::from(version)).then_some(path)
+        })
+    }
+
+    /// Handle prepareRename request
+    ///
+    /// Servers that don't advertise prepareRename support are not asked; the
+    /// main loop falls back to the word under the cursor.
+    async fn handle_prepare_rename(
+        &mut self,
+        request_id: u64,
+        uri: Uri,
+        line: u32,
+        character: u32,
+        pending: &Arc<Mutex<HashMap<i64, oneshot::Sender<Result<Value, String>>>>>,
+    ) -> Result<(), String> {
+        use lsp_types::{OneOf, Position, TextDocumentIdentifier, TextDocumentPositionParams};
+
+        let supported = self
+            .capabilities
+            .as_ref()
+            .and_then(|c| c.rename_provider.as_ref())
+            .is_some_and(|provider| match provider {
+                OneOf::Left(_) => false,
+                OneOf::Right(options) => options.prepare_provider == Some(true),
+            });
+        if !supported {
+            let _ = self.async_tx.send(AsyncMessage::LspPrepareRename {
+                request_id,
+                supported: false,
+                result: Ok(None),
+            });
+            return Ok(());
+        }
+
+        tracing::trace!(
+            "LSP: prepareRename request at {}:{}:{}",
+            uri.as_str(),
+            line,
+            character
+        );
+
+        let params = TextDocumentPositionParams {
+            text_document: TextDocumentIdentifier { uri },
+            position: Position { line, character },
+        };
+        let result = self
+            .send_request_sequential::<_, Value>(
+                "textDocument/prepareRename",
+                Some(params),
+                pending,
+            )
+            .await
+            .and_then(|value| {
+                serde_json::from_value::<Option<lsp_types::PrepareRenameResponse>>(value)
+                    .map_err(|e| format!("Failed to parse prepareRename response: {}", e))
+            });
+        if let Err(e) = &result {
+            tracing::warn!("prepareRename request failed: {}", e);
+        }
+        let _ = self.async_tx.send(AsyncMessage::LspPrepareRename {
+            request_id,
+            supported: true,
+            result: result.clone(),
+        });
+        result.map(|_| ())
+    }
+
     /// Handle rename request
     async fn handle_rename(
         &mut self,
@@ -889,14 +1115,22 @@ impl LspState {
             .await
         {
             Ok(result) => {
-                // Parse the workspace edit response
-                match serde_json::from_value::<lsp_types::WorkspaceEdit>(result) {
+                // Parse the workspace edit response (null means nothing to rename,
+                // which the main loop reports like an empty edit)
+                match serde_json::from_value::<Option<lsp_types::WorkspaceEdit>>(result) {
                     Ok(workspace_edit) => {
+                        let workspace_edit = workspace_edit.unwrap_or_default();
+                        let result = match self.stale_document_edit(&workspace_edit) {
+                            Some(path) => Err(format!(
+                                "{} changed since the rename was requested",
+                                path.display()
+                            )),
+                            None => Ok(workspace_edit),
+                        };
                         // Send to main loop
-                        let _ = self.async_tx.send(AsyncMessage::LspRename {
-                            request_id,
-                            result: Ok(workspace_edit),
-                        });
+                        let _ = self
+                            .async_tx
+                            .send(AsyncMessage::LspRename { request_id, result });
                         Ok(())
                     }
                     Err(e) => {
@@ -921,6 +1155,97 @@ impl LspState {
         }
     }
 
+    /// Handle on-type formatting request
+    ///
+    /// Servers that don't advertise the typed character as a trigger are not
+    /// asked; the main loop gets an empty edit list instead.
+    #[allow(clippy::too_many_arguments)]
+    async fn handle_on_type_formatting(
+        &mut self,
+        request_id: u64,
+        uri: Uri,
+        line: u32,
+        character: u32,
+        ch: char,
+        tab_size: u32,
+        insert_spaces: bool,
+        pending: &Arc<Mutex<HashMap<i64, oneshot::Sender<Result<Value, String>>>>>,
+    ) -> Result<(), String> {
+        use lsp_types::{
+            DocumentOnTypeFormattingParams, FormattingOptions, Position, TextDocumentIdentifier,
+            TextDocumentPositionParams,
+        };
+
+        let ch = ch.to_string();
+        let is_trigger = self
+            .capabilities
+            .as_ref()
+            .and_then(|c| c.document_on_type_formatting_provider.as_ref())
+            .is_some_and(|provider| {
+                provider.first_trigger_character == ch
+                    || provider
+                        .more_trigger_character
+                        .as_ref()
+                        .is_some_and(|more| more.contains(&ch))
+            });
+        if !is_trigger {
+            tracing::trace!(
+                "LSP: {:?} is not an on-type formatting trigger for {}",
+                ch,
+                uri.as_str()
+            );
+            let _ = self.async_tx.send(AsyncMessage::LspOnTypeFormatting {
+                request_id,
+                edits: Vec::new(),
+            });
+            return Ok(());
+        }
+
+        tracing::trace!(
+            "LSP: on-type formatting request at {}:{}:{} for {:?}",
+            uri.as_str(),
+            line,
+            character,
+            ch
+        );
+
+        let params = DocumentOnTypeFormattingParams {
+            text_document_position: TextDocumentPositionParams {
+                text_document: TextDocumentIdentifier { uri },
+                position: Position { line, character },
+            },
+            ch,
+            options: FormattingOptions {
+                tab_size,
+                insert_spaces,
+                ..Default::default()
+            },
+        };
+
+        let result = self
+            .send_request_sequential::<_, Value>(
+                "textDocument/onTypeFormatting",
+                Some(params),
+                pending,
+            )
+            .await
+            .and_then(|value| {
+                serde_json::from_value::<Option<Vec<lsp_types::TextEdit>>>(value)
+                    .map_err(|e| format!("Failed to parse on-type formatting response: {}", e))
+            });
+        let edits = match &result {
+            Ok(edits) => edits.clone().unwrap_or_default(),
+            Err(e) => {
+                tracing::warn!("On-type formatting request failed: {}", e);
+                Vec::new()
+            }
+        };
+        let _ = self
+            .async_tx
+            .send(AsyncMessage::LspOnTypeFormatting { request_id, edits });
+        result.map(|_| ())
+    }
+
     /// Handle hover documentation request
     async fn handle_hover(
         &mut self,
@@ -1183,6 +1508,7 @@ impl LspState {
     }
 
     /// Handle code actions request
+    #[allow(clippy::too_many_arguments)]
     async fn handle_code_actions(
         &mut self,
         request_id: u64,
@@ -1192,6 +1518,7 @@ impl LspState {
         end_line: u32,
         end_char: u32,
         diagnostics: Vec<lsp_types::Diagnostic>,
+        only: Option<Vec<lsp_types::CodeActionKind>>,
         pending: &Arc<Mutex<HashMap<i64, oneshot::Sender<Result<Value, String>>>>>,
     ) -> Result<(), String> {
         use lsp_types::{
@@ -1222,7 +1549,7 @@ impl LspState {
             },
             context: CodeActionContext {
                 diagnostics,
-                only: None,
+                only,
                 trigger_kind: None,
             },
             work_done_progress_params: WorkDoneProgressParams::default(),
@@ -1331,6 +1658,7 @@ impl LspState {
                     );
 
                     let _ = self.async_tx.send(AsyncMessage::LspPulledDiagnostics {
+                        language: self.language.clone(),
                         request_id,
                         uri: uri_string,
                         result_id,
@@ -1352,6 +1680,7 @@ impl LspState {
                     );
 
                     let _ = self.async_tx.send(AsyncMessage::LspPulledDiagnostics {
+                        language: self.language.clone(),
                         request_id,
                         uri: uri_string,
                         result_id: Some(result_id),
@@ -1365,6 +1694,7 @@ impl LspState {
                         result
                     );
                     let _ = self.async_tx.send(AsyncMessage::LspPulledDiagnostics {
+                        language: self.language.clone(),
                         request_id,
                         uri: uri_string,
                         result_id: None,
@@ -1379,6 +1709,7 @@ impl LspState {
                 tracing::error!("Document diagnostic request failed: {}", e);
                 // Send empty result on error
                 let _ = self.async_tx.send(AsyncMessage::LspPulledDiagnostics {
+                    language: self.language.clone(),
                     request_id,
                     uri: uri.as_str().to_string(),
                     result_id: None,
@@ -1504,16 +1835,32 @@ impl LspState {
     }
 
     /// Handle shutdown command
-    async fn handle_shutdown(&mut self) -> Result<(), String> {
-        tracing::info!("Shutting down async LSP server");
-
-        let notification = JsonRpcNotification {
-            jsonrpc: "2.0".to_string(),
-            method: "shutdown".to_string(),
-            params: None,
-        };
+    ///
+    /// Sends the `shutdown` request and, once it is answered or has timed
+    /// out, the `exit` notification. A server that was never initialized
+    /// only gets `exit`.
+    async fn handle_shutdown(
+        &mut self,
+        pending: &Arc<Mutex<HashMap<i64, oneshot::Sender<Result<Value, String>>>>>,
+    ) -> Result<(), String> {
+        tracing::info!("Shutting down async LSP server for {}", self.language);
 
-        self.write_message(&notification).await?;
+        if self.initialized {
+            let response = tokio::time::timeout(
+                SHUTDOWN_RESPONSE_TIMEOUT,
+                self.send_request_sequential::<(), Value>(Shutdown::METHOD, None, pending),
+            )
+            .await;
+            match response {
+                Ok(Ok(_)) => {}
+                Ok(Err(e)) => tracing::warn!("LSP shutdown request failed: {}", e),
+                Err(_) => tracing::warn!(
+                    "LSP server for {} did not answer shutdown within {:?}",
+                    self.language,
+                    SHUTDOWN_RESPONSE_TIMEOUT
+                ),
+            }
+        }
 
         let exit = JsonRpcNotification {
             jsonrpc: "2.0".to_string(),
@@ -1556,6 +1903,47 @@ impl LspState {
     }
 }
 
+/// Tells a hung server from a busy one between health checks
+///
+/// A server is unresponsive when the same request has been waiting for
+/// [`UNRESPONSIVE_AFTER_CHECKS`] checks in a row without a single message
+/// from the server; progress or diagnostics in the meantime show it's
+/// still working.
+#[derive(Debug, Default)]
+struct ResponsivenessCheck {
+    /// Oldest request waiting for an answer at the last check
+    waiting_on: Option<i64>,
+    /// Messages read from the server as of the last check
+    messages_seen: u64,
+    /// Checks in a row the same request waited in silence
+    silent_checks: u32,
+    unresponsive: bool,
+}
+
+impl ResponsivenessCheck {
+    /// Record a check; returns the new verdict when it changed
+    fn check(&mut self, oldest_pending: Option<i64>, messages_received: u64) -> Option<bool> {
+        if oldest_pending.is_some()
+            && oldest_pending == self.waiting_on
+            && messages_received == self.messages_seen
+        {
+            self.silent_checks += 1;
+        } else {
+            self.silent_checks = 0;
+        }
+        self.waiting_on = oldest_pending;
+        self.messages_seen = messages_received;
+
+        let unresponsive = self.silent_checks >= UNRESPONSIVE_AFTER_CHECKS;
+        if unresponsive != self.unresponsive {
+            self.unresponsive = unresponsive;
+            Some(unresponsive)
+        } else {
+            None
+        }
+    }
+}
+
 /// Async LSP task that handles all I/O
 struct LspTask {
     /// Process handle
@@ -1722,6 +2110,10 @@ impl LspTask {
     }
 
     /// Spawn the stdout reader task that continuously reads and dispatches LSP messages
+    ///
+    /// `shutting_down` is set once the server is known to be going away, by
+    /// whoever notices first, so its end is reported only once.
+    #[allow(clippy::too_many_arguments)]
     fn spawn_stdout_reader(
         mut stdout: BufReader<ChildStdout>,
         pending: Arc<Mutex<HashMap<i64, oneshot::Sender<Result<Value, String>>>>>,
@@ -1731,6 +2123,7 @@ impl LspTask {
         server_response_tx: mpsc::Sender<JsonRpcResponse>,
         stderr_log_path: std::path::PathBuf,
         shutting_down: Arc<AtomicBool>,
+        messages_received: Arc<AtomicU64>,
     ) {
         tokio::spawn(async move {
             tracing::info!("LSP stdout reader task started for {}", language);
@@ -1738,6 +2131,7 @@ impl LspTask {
                 match read_message_from_stdout(&mut stdout).await {
                     Ok(message) => {
                         tracing::trace!("Read message from LSP server: {:?}", message);
+                        messages_received.fetch_add(1, Ordering::SeqCst);
                         if let Err(e) = handle_message_dispatch(
                             message,
                             &pending,
@@ -1753,7 +2147,7 @@ impl LspTask {
                     }
                     Err(e) => {
                         // Only report error if this wasn't an intentional shutdown
-                        if shutting_down.load(Ordering::SeqCst) {
+                        if shutting_down.swap(true, Ordering::SeqCst) {
                             tracing::info!(
                                 "LSP stdout reader exiting due to graceful shutdown for {}",
                                 language
@@ -1804,6 +2198,7 @@ impl LspTask {
 
         // Flag to indicate intentional shutdown (prevents spurious error messages)
         let shutting_down = Arc::new(AtomicBool::new(false));
+        let messages_received = Arc::new(AtomicU64::new(0));
 
         // Spawn stdout reader task
         Self::spawn_stdout_reader(
@@ -1813,14 +2208,67 @@ impl LspTask {
             language_clone.clone(),
             self.server_command.clone(),
             server_response_tx,
-            self.stderr_log_path,
+            self.stderr_log_path.clone(),
             shutting_down.clone(),
+            messages_received.clone(),
+        );
+
+        let mut process = self.process;
+        let mut health_check = tokio::time::interval_at(
+            tokio::time::Instant::now() + HEALTH_CHECK_INTERVAL,
+            HEALTH_CHECK_INTERVAL,
         );
+        health_check.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        let mut responsiveness = ResponsivenessCheck::default();
+        let mut shutdown = None;
 
         // Sequential command processing loop with server response handling
         let mut pending_commands = Vec::new();
         loop {
             tokio::select! {
+                // Check that the server is still alive and answering
+                _ = health_check.tick() => {
+                    match process.try_wait() {
+                        Ok(Some(status)) => {
+                            if !shutting_down.swap(true, Ordering::SeqCst) {
+                                tracing::error!("LSP server for {} exited: {}", language_clone, status);
+                                let _ = async_tx.send(AsyncMessage::LspStatusUpdate {
+                                    language: language_clone.clone(),
+                                    status: LspServerStatus::Error,
+                                });
+                                let _ = async_tx.send(AsyncMessage::LspError {
+                                    language: language_clone.clone(),
+                                    error: format!("Server exited: {}", status),
+                                    stderr_log_path: Some(self.stderr_log_path.clone()),
+                                });
+                            }
+                            break;
+                        }
+                        Ok(None) => {
+                            let oldest_pending = pending.lock().unwrap().keys().min().copied();
+                            let verdict = responsiveness
+                                .check(oldest_pending, messages_received.load(Ordering::SeqCst));
+                            if let Some(unresponsive) = verdict {
+                                let status = if unresponsive {
+                                    tracing::warn!("LSP server for {} is not responding", language_clone);
+                                    LspServerStatus::Unresponsive
+                                } else if state.initialized {
+                                    tracing::info!("LSP server for {} is responding again", language_clone);
+                                    LspServerStatus::Running
+                                } else {
+                                    LspServerStatus::Initializing
+                                };
+                                let _ = async_tx.send(AsyncMessage::LspStatusUpdate {
+                                    language: language_clone.clone(),
+                                    status,
+                                });
+                            }
+                        }
+                        Err(e) => {
+                            tracing::warn!("Failed to check LSP server process: {}", e);
+                        }
+                    }
+                }
                 // Handle server-to-client responses (high priority)
                 Some(response) = server_response_rx.recv() => {
                     tracing::trace!("Sending response to server request id={}", response.id);
@@ -1829,10 +2277,14 @@ impl LspTask {
                     }
                 }
                 // Handle commands from the editor
-                Some(cmd) = command_rx.recv() => {
+                cmd = command_rx.recv() => {
+                    let Some(cmd) = cmd else {
+                        tracing::info!("Command channel closed");
+                        break;
+                    };
                     tracing::trace!("LspTask received command: {:?}", cmd);
                     match cmd {
-                        LspCommand::Initialize { root_uri, initialization_options, response } => {
+                        LspCommand::Initialize { root_uri, initialization_options, position_encodings, response } => {
                             // Send initializing status
                             let _ = async_tx.send(AsyncMessage::LspStatusUpdate {
                                 language: language_clone.clone(),
@@ -1840,7 +2292,7 @@ impl LspTask {
                             });
                             tracing::info!("Processing Initialize command");
                             let result =
-                                state.handle_initialize_sequential(root_uri, initialization_options, &pending).await;
+                                state.handle_initialize_sequential(root_uri, initialization_options, position_encodings, &pending).await;
                             let success = result.is_ok();
                             let _ = response.send(result);
 
@@ -1904,6 +2356,18 @@ impl LspTask {
                                 pending_commands.push(LspCommand::DidSave { uri, text });
                             }
                         }
+                        LspCommand::DidClose { uri } => {
+                            if state.initialized {
+                                tracing::info!("Processing DidClose for {}", uri.as_str());
+                                let _ = state.handle_did_close(uri).await;
+                            } else {
+                                tracing::trace!(
+                                    "Queueing DidClose for {} until initialization completes",
+                                    uri.as_str()
+                                );
+                                pending_commands.push(LspCommand::DidClose { uri });
+                            }
+                        }
                         LspCommand::Completion {
                             request_id,
                             uri,
@@ -1950,6 +2414,27 @@ impl LspTask {
                                 });
                             }
                         }
+                        LspCommand::PrepareRename {
+                            request_id,
+                            uri,
+                            line,
+                            character,
+                        } => {
+                            if state.initialized {
+                                let _ = state
+                                    .handle_prepare_rename(
+                                        request_id, uri, line, character, &pending,
+                                    )
+                                    .await;
+                            } else {
+                                tracing::trace!("LSP not initialized, cannot prepare rename");
+                                let _ = state.async_tx.send(AsyncMessage::LspPrepareRename {
+                                    request_id,
+                                    supported: false,
+                                    result: Ok(None),
+                                });
+                            }
+                        }
                         LspCommand::Rename {
                             request_id,
                             uri,
@@ -1972,6 +2457,36 @@ impl LspTask {
                                 });
                             }
                         }
+                        LspCommand::OnTypeFormatting {
+                            request_id,
+                            uri,
+                            line,
+                            character,
+                            ch,
+                            tab_size,
+                            insert_spaces,
+                        } => {
+                            if state.initialized {
+                                let _ = state
+                                    .handle_on_type_formatting(
+                                        request_id,
+                                        uri,
+                                        line,
+                                        character,
+                                        ch,
+                                        tab_size,
+                                        insert_spaces,
+                                        &pending,
+                                    )
+                                    .await;
+                            } else {
+                                tracing::trace!("LSP not initialized, skipping on-type formatting");
+                                let _ = state.async_tx.send(AsyncMessage::LspOnTypeFormatting {
+                                    request_id,
+                                    edits: Vec::new(),
+                                });
+                            }
+                        }
                         LspCommand::Hover {
                             request_id,
                             uri,
@@ -2039,6 +2554,7 @@ impl LspTask {
                             end_line,
                             end_char,
                             diagnostics,
+                            only,
                         } => {
                             if state.initialized {
                                 tracing::info!("Processing CodeActions request for {}", uri.as_str());
@@ -2051,6 +2567,7 @@ impl LspTask {
                                         end_line,
                                         end_char,
                                         diagnostics,
+                                        only,
                                         &pending,
                                     )
                                     .await;
@@ -2085,6 +2602,7 @@ impl LspTask {
                                     "LSP not initialized, cannot get document diagnostics"
                                 );
                                 let _ = state.async_tx.send(AsyncMessage::LspPulledDiagnostics {
+                                    language: state.language.clone(),
                                     request_id,
                                     uri: uri.as_str().to_string(),
                                     result_id: None,
@@ -2166,31 +2684,60 @@ impl LspTask {
                                 });
                             }
                         }
-                        LspCommand::Shutdown => {
+                        LspCommand::Shutdown { done } => {
                             tracing::info!("Processing Shutdown command");
                             // Set flag before shutdown to prevent spurious error messages
                             shutting_down.store(true, Ordering::SeqCst);
-                            let _ = state.handle_shutdown().await;
+                            let _ = state.handle_shutdown(&pending).await;
+                            shutdown = Some(done);
                             break;
                         }
                     }
                 }
-                // Handle channel closure
-                else => {
-                    tracing::info!("Command channel closed");
-                    break;
-                }
+            }
+        }
+
+        // Closing stdin as well tells a server that missed `exit` to stop
+        drop(state);
+        if let Some(done) = shutdown {
+            Self::wait_for_exit(&mut process, &self.language).await;
+            if let Some(done) = done {
+                let _ = done.send(());
             }
         }
 
         tracing::info!("LSP task exiting for language: {}", self.language);
     }
 
+    /// Wait for the server process to exit after `exit`, killing it if it
+    /// doesn't within [`EXIT_TIMEOUT`]
+    async fn wait_for_exit(process: &mut Child, language: &str) {
+        match tokio::time::timeout(EXIT_TIMEOUT, process.wait()).await {
+            Ok(Ok(status)) => {
+                tracing::info!("LSP server for {} exited: {}", language, status);
+            }
+            Ok(Err(e)) => {
+                tracing::warn!("Failed to wait for LSP server for {}: {}", language, e);
+            }
+            Err(_) => {
+                tracing::warn!(
+                    "LSP server for {} did not exit within {:?}, killing it",
+                    language,
+                    EXIT_TIMEOUT
+                );
+                if let Err(e) = process.kill().await {
+                    tracing::warn!("Failed to kill LSP server for {}: {}", language, e);
+                }
+            }
+        }
+    }
+
     /// Sequential version of handle_initialize that uses shared pending map
     async fn handle_initialize_sequential(
         &mut self,
         root_uri: Option<Uri>,
         initialization_options: Option<Value>,
+        position_encodings: Vec<PositionEncodingKind>,
         pending: &Arc<Mutex<HashMap<i64, oneshot::Sender<Result<Value, String>>>>>,
     ) -> Result<InitializeResult, String> {
         tracing::info!(
@@ -2214,7 +2761,7 @@ impl LspTask {
 
         let params = InitializeParams {
             process_id: Some(std::process::id()),
-            capabilities: create_client_capabilities(),
+            capabilities: create_client_capabilities(position_encodings),
             workspace_folders,
             initialization_options,
             ..Default::default()
@@ -2233,6 +2780,23 @@ impl LspTask {
         self.initialized = true;
 
         // Notify main loop
+        let _ = self.async_tx.send(AsyncMessage::LspCompletionTriggers {
+            language: self.language.clone(),
+            characters: result
+                .capabilities
+                .completion_provider
+                .as_ref()
+                .and_then(|provider| provider.trigger_characters.clone())
+                .unwrap_or_default(),
+        });
+        let _ = self.async_tx.send(AsyncMessage::LspCodeActionKinds {
+            language: self.language.clone(),
+            kinds: code_action_kinds(&result.capabilities),
+        });
+        let _ = self.async_tx.send(AsyncMessage::LspServerSync {
+            language: self.language.clone(),
+            sync: ServerSync::from_capabilities(&result.capabilities),
+        });
         let _ = self.async_tx.send(AsyncMessage::LspInitialized {
             language: self.language.clone(),
         });
@@ -2256,7 +2820,7 @@ impl LspTask {
         language_id: String,
         _pending: &Arc<Mutex<HashMap<i64, oneshot::Sender<Result<Value, String>>>>>,
     ) -> Result<(), String> {
-        let path = PathBuf::from(uri.path().as_str());
+        let path = document_path(&uri);
 
         if should_skip_did_open(&self.document_versions, &path, &self.language, &uri) {
             return Ok(());
@@ -2290,7 +2854,7 @@ impl LspTask {
     ) -> Result<(), String> {
         tracing::trace!("LSP: did_change for {}", uri.as_str());
 
-        let path = PathBuf::from(uri.path().as_str());
+        let path = document_path(&uri);
 
         // Check if this document was recently opened and wait if needed
         // This prevents race conditions where the server receives didChange
@@ -2552,6 +3116,7 @@ impl LspTask {
 
                     // Send to main loop
                     let _ = self.async_tx.send(AsyncMessage::LspDiagnostics {
+                        language: self.language.clone(),
                         uri: params.uri.to_string(),
                         diagnostics: params.diagnostics,
                     });
@@ -2955,6 +3520,7 @@ async fn handle_notification_dispatch(
 
                 // Send to main loop
                 let _ = async_tx.send(AsyncMessage::LspDiagnostics {
+                    language: language.to_string(),
                     uri: params.uri.to_string(),
                     diagnostics: params.diagnostics,
                 });
@@ -3179,6 +3745,9 @@ pub struct LspHandle {
 
 impl LspHandle {
     /// Spawn a new LSP server in an async task
+    ///
+    /// `language` labels the server in the messages it sends: its language,
+    /// or `language/name` when the language has several servers.
     pub fn spawn(
         runtime: &tokio::runtime::Handle,
         command: &str,
@@ -3195,7 +3764,7 @@ impl LspHandle {
         let state = Arc::new(Mutex::new(LspClientState::Starting));
 
         // Create stderr log path in XDG state directory
-        let stderr_log_path = crate::services::log_dirs::lsp_log_path(&language);
+        let stderr_log_path = crate::services::log_dirs::lsp_log_path(&language.replace('/', "-"));
 
         // Send starting status
         let _ = async_tx.send(AsyncMessage::LspStatusUpdate {
@@ -3261,12 +3830,14 @@ impl LspHandle {
     /// when `is_initialized()` returns true. Other methods that require initialization
     /// will fail gracefully until then.
     ///
-    /// The `initialization_options` are passed to the server during initialization.
+    /// The `initialization_options` are passed to the server during initialization,
+    /// and it picks the encoding of positions among `position_encodings`.
     /// Some servers like Deno require specific options (e.g., `{"enable": true}`).
     pub fn initialize(
         &self,
         root_uri: Option<Uri>,
         initialization_options: Option<Value>,
+        position_encodings: Vec<PositionEncodingKind>,
     ) -> Result<(), String> {
         // Validate state transition
         {
@@ -3290,6 +3861,7 @@ impl LspHandle {
             .try_send(LspCommand::Initialize {
                 root_uri,
                 initialization_options,
+                position_encodings,
                 response: tx,
             })
             .map_err(|_| "Failed to send initialize command".to_string())?;
@@ -3373,6 +3945,13 @@ impl LspHandle {
             .map_err(|_| "Failed to send did_save command".to_string())
     }
 
+    /// Send didClose notification
+    pub fn did_close(&self, uri: Uri) -> Result<(), String> {
+        self.command_tx
+            .try_send(LspCommand::DidClose { uri })
+            .map_err(|_| "Failed to send did_close command".to_string())
+    }
+
     /// Request completion at position
     pub fn completion(
         &self,
@@ -3409,6 +3988,24 @@ impl LspHandle {
             .map_err(|_| "Failed to send goto_definition command".to_string())
     }
 
+    /// Request prepareRename (answered as unsupported if the server lacks it)
+    pub fn prepare_rename(
+        &self,
+        request_id: u64,
+        uri: Uri,
+        line: u32,
+        character: u32,
+    ) -> Result<(), String> {
+        self.command_tx
+            .try_send(LspCommand::PrepareRename {
+                request_id,
+                uri,
+                line,
+                character,
+            })
+            .map_err(|_| "Failed to send prepare_rename command".to_string())
+    }
+
     /// Request rename
     pub fn rename(
         &self,
@@ -3429,6 +4026,31 @@ impl LspHandle {
             .map_err(|_| "Failed to send rename command".to_string())
     }
 
+    /// Request formatting edits after typing `ch` (LSP onTypeFormatting)
+    #[allow(clippy::too_many_arguments)]
+    pub fn on_type_formatting(
+        &self,
+        request_id: u64,
+        uri: Uri,
+        line: u32,
+        character: u32,
+        ch: char,
+        tab_size: u32,
+        insert_spaces: bool,
+    ) -> Result<(), String> {
+        self.command_tx
+            .try_send(LspCommand::OnTypeFormatting {
+                request_id,
+                uri,
+                line,
+                character,
+                ch,
+                tab_size,
+                insert_spaces,
+            })
+            .map_err(|_| "Failed to send on_type_formatting command".to_string())
+    }
+
     /// Request hover documentation
     pub fn hover(
         &self,
@@ -3483,7 +4105,8 @@ impl LspHandle {
             .map_err(|_| "Failed to send signature_help command".to_string())
     }
 
-    /// Request code actions
+    /// Request code actions, optionally only those of some kinds
+    #[allow(clippy::too_many_arguments)]
     pub fn code_actions(
         &self,
         request_id: u64,
@@ -3493,6 +4116,7 @@ impl LspHandle {
         end_line: u32,
         end_char: u32,
         diagnostics: Vec<lsp_types::Diagnostic>,
+        only: Option<Vec<lsp_types::CodeActionKind>>,
     ) -> Result<(), String> {
         self.command_tx
             .try_send(LspCommand::CodeActions {
@@ -3503,6 +4127,7 @@ impl LspHandle {
                 end_line,
                 end_char,
                 diagnostics,
+                only,
             })
             .map_err(|_| "Failed to send code_actions command".to_string())
     }
@@ -3592,7 +4217,22 @@ impl LspHandle {
     }
 
     /// Shutdown the server
+    ///
+    /// The `shutdown` request, `exit` notification and wait for the process
+    /// run in the background; see [`Self::shutdown_notify`] to wait for them.
     pub fn shutdown(&self) -> Result<(), String> {
+        self.send_shutdown(None)
+    }
+
+    /// Shutdown the server, returning a receiver signalled once its process
+    /// is gone (at most [`GRACEFUL_SHUTDOWN_TIMEOUT`] later)
+    pub fn shutdown_notify(&self) -> Result<std_mpsc::Receiver<()>, String> {
+        let (done_tx, done_rx) = std_mpsc::channel();
+        self.send_shutdown(Some(done_tx))?;
+        Ok(done_rx)
+    }
+
+    fn send_shutdown(&self, done: Option<std_mpsc::Sender<()>>) -> Result<(), String> {
         // Transition to Stopping state
         {
             let mut state = self.state.lock().unwrap();
@@ -3603,7 +4243,7 @@ impl LspHandle {
         }
 
         self.command_tx
-            .try_send(LspCommand::Shutdown)
+            .try_send(LspCommand::Shutdown { done })
             .map_err(|_| "Failed to send shutdown command".to_string())?;
 
         // Transition to Stopped state
@@ -3624,7 +4264,9 @@ impl Drop for LspHandle {
         // 1. The tokio runtime is shut down
         // 2. The channel is full or closed
         // 3. We're dropping during a panic
-        let _ = self.command_tx.try_send(LspCommand::Shutdown);
+        let _ = self
+            .command_tx
+            .try_send(LspCommand::Shutdown { done: None });
 
         // Update state to Stopped
         if let Ok(mut state) = self.state.lock() {
@@ -3636,6 +4278,7 @@ impl Drop for LspHandle {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::services::lsp::document_sync::client_position_encodings;
 
     #[test]
     fn test_json_rpc_request_serialization() {
@@ -3958,7 +4601,7 @@ mod tests {
     #[test]
     fn test_lsp_command_debug_format() {
         // Test that LspCommand has Debug implementation
-        let cmd = LspCommand::Shutdown;
+        let cmd = LspCommand::Shutdown { done: None };
         let debug_str = format!("{:?}", cmd);
         assert!(debug_str.contains("Shutdown"));
     }
@@ -4015,7 +4658,7 @@ mod tests {
 
         // Immediately call initialize - this is what get_or_spawn() does
         // Before the fix, this would fail with "Cannot initialize: client is in state Starting"
-        let result = handle.initialize(None, None);
+        let result = handle.initialize(None, None, client_position_encodings());
 
         assert!(
             result.is_ok(),
@@ -4063,7 +4706,7 @@ mod tests {
         .unwrap();
 
         // This is the critical test: initialize must succeed from Starting state
-        let init_result = handle.initialize(None, None);
+        let init_result = handle.initialize(None, None, client_position_encodings());
         assert!(
             init_result.is_ok(),
             "initialize() failed from Starting state: {:?}",
@@ -4087,4 +4730,191 @@ mod tests {
         // Cleanup
         let _ = handle.shutdown();
     }
+
+    /// Mock server for the shutdown tests: logs the methods it receives to
+    /// the file given as its first argument. With "hang" as second argument
+    /// it ignores `shutdown` and everything after it.
+    const SHUTDOWN_MOCK_SERVER: &str = r#"
+        log="$1"
+        echo "pid $$" >> "$log"
+
+        read_message() {
+            local content_length=0
+            while IFS=: read -r key value; do
+                key=$(echo "$key" | tr -d '\r\n')
+                value=$(echo "$value" | tr -d '\r\n ')
+                if [ "$key" = "Content-Length" ]; then
+                    content_length=$value
+                fi
+                if [ -z "$key" ]; then
+                    break
+                fi
+            done
+            if [ $content_length -gt 0 ]; then
+                dd bs=1 count=$content_length 2>/dev/null
+            fi
+        }
+
+        send_message() {
+            echo -en "Content-Length: ${#1}\r\n\r\n$1"
+        }
+
+        while true; do
+            msg=$(read_message)
+            if [ -z "$msg" ]; then
+                break
+            fi
+            method=$(echo "$msg" | grep -o '"method":"[^"]*"' | cut -d'"' -f4)
+            msg_id=$(echo "$msg" | grep -o '"id":[0-9]*' | cut -d':' -f2)
+            echo "$method" >> "$log"
+            case "$method" in
+                "initialize")
+                    send_message '{"jsonrpc":"2.0","id":'$msg_id',"result":{"capabilities":{}}}'
+                    ;;
+                "shutdown")
+                    if [ "$2" = "hang" ]; then
+                        exec sleep 30
+                    fi
+                    sleep 0.3
+                    echo "answered shutdown" >> "$log"
+                    send_message '{"jsonrpc":"2.0","id":'$msg_id',"result":null}'
+                    ;;
+                "exit")
+                    exit 0
+                    ;;
+            esac
+        done
+    "#;
+
+    /// Start the shutdown mock server and wait until it is initialized
+    fn spawn_shutdown_mock(
+        runtime: &tokio::runtime::Runtime,
+        log: &std::path::Path,
+        mode: &str,
+    ) -> LspHandle {
+        let async_bridge = AsyncBridge::new();
+        let handle = LspHandle::spawn(
+            runtime.handle(),
+            "bash",
+            &[
+                "-c".to_string(),
+                SHUTDOWN_MOCK_SERVER.to_string(),
+                "mock-lsp".to_string(),
+                log.to_string_lossy().to_string(),
+                mode.to_string(),
+            ],
+            "mock".to_string(),
+            &async_bridge,
+            ProcessLimits::unlimited(),
+        )
+        .unwrap();
+        handle
+            .initialize(None, None, client_position_encodings())
+            .unwrap();
+
+        let start = Instant::now();
+        while handle.state() != LspClientState::Running {
+            assert!(
+                start.elapsed() < Duration::from_secs(10),
+                "mock server never initialized"
+            );
+            std::thread::sleep(Duration::from_millis(20));
+        }
+        handle
+    }
+
+    fn logged_lines(log: &std::path::Path) -> Vec<String> {
+        std::fs::read_to_string(log)
+            .unwrap_or_default()
+            .lines()
+            .map(str::to_string)
+            .collect()
+    }
+
+    fn is_process_alive(pid: &str) -> bool {
+        std::process::Command::new("kill")
+            .args(["-0", pid])
+            .stderr(std::process::Stdio::null())
+            .status()
+            .map(|status| status.success())
+            .unwrap_or(false)
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_shutdown_sends_exit_after_shutdown_is_answered() {
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let temp_dir = tempfile::tempdir().unwrap();
+        let log = temp_dir.path().join("methods.log");
+
+        let handle = spawn_shutdown_mock(&runtime, &log, "answer");
+        let done = handle.shutdown_notify().unwrap();
+        done.recv_timeout(GRACEFUL_SHUTDOWN_TIMEOUT)
+            .expect("shutdown finished in time");
+
+        let lines = logged_lines(&log);
+        let position = |line: &str| {
+            lines
+                .iter()
+                .position(|l| l == line)
+                .unwrap_or_else(|| panic!("{:?} not in {:?}", line, lines))
+        };
+        assert!(position("initialized") < position("shutdown"));
+        assert!(position("shutdown") < position("answered shutdown"));
+        assert!(position("answered shutdown") < position("exit"));
+
+        let pid = lines[0].strip_prefix("pid ").unwrap();
+        assert!(!is_process_alive(pid));
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_shutdown_kills_server_that_ignores_it() {
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let temp_dir = tempfile::tempdir().unwrap();
+        let log = temp_dir.path().join("methods.log");
+
+        let handle = spawn_shutdown_mock(&runtime, &log, "hang");
+        let pid = logged_lines(&log)[0]
+            .strip_prefix("pid ")
+            .unwrap()
+            .to_string();
+
+        let start = Instant::now();
+        let done = handle.shutdown_notify().unwrap();
+        done.recv_timeout(GRACEFUL_SHUTDOWN_TIMEOUT)
+            .expect("shutdown finished in time");
+
+        // The server got the full response and exit timeouts before the kill
+        assert!(start.elapsed() >= SHUTDOWN_RESPONSE_TIMEOUT + EXIT_TIMEOUT);
+        assert!(logged_lines(&log).contains(&"shutdown".to_string()));
+        assert!(!is_process_alive(&pid));
+    }
+
+    #[test]
+    fn test_responsiveness_check() {
+        let mut check = ResponsivenessCheck::default();
+
+        // Nothing waiting: never unresponsive, however quiet
+        for _ in 0..10 {
+            assert_eq!(check.check(None, 0), None);
+        }
+
+        // The same request waiting in silence
+        assert_eq!(check.check(Some(4), 0), None);
+        for _ in 1..UNRESPONSIVE_AFTER_CHECKS {
+            assert_eq!(check.check(Some(4), 0), None);
+        }
+        assert_eq!(check.check(Some(4), 0), Some(true));
+        assert_eq!(check.check(Some(4), 0), None);
+
+        // Any message from the server means it's alive again
+        assert_eq!(check.check(Some(4), 1), Some(false));
+
+        // A server that keeps talking while working on a request is busy,
+        // not hung
+        for messages in 2..10 {
+            assert_eq!(check.check(Some(4), messages), None);
+        }
+    }
 }
@@ -0,0 +1,397 @@
+//! Diagnostics of the whole workspace, indexed by file
+//!
+//! Language servers publish diagnostics per URI, and may spell one file in
+//! several ways (`%20` or a space, `c%3A` or `C:`), or send URIs that don't
+//! name a local file at all (`untitled:`, virtual documents). The store keys
+//! `file://` URIs by the path they name, so every spelling of a file lands
+//! on one entry, and keeps any other URI as it was normalized. Counts per
+//! severity are kept per file and for the workspace, so the status bar,
+//! plugins and the workspace diagnostics panel read them without walking the
+//! diagnostics. When several servers publish for one file, the file's
+//! diagnostics are those of all of them.
+
+use super::uri::{normalize_uri, path_to_uri, uri_to_path};
+use lsp_types::{Diagnostic, DiagnosticSeverity};
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// File a set of diagnostics belongs to
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum DiagnosticFile {
+    /// Local file, by the path its URI names
+    Path(PathBuf),
+    /// URI that doesn't name a local file, normalized
+    Uri(String),
+}
+
+impl DiagnosticFile {
+    /// Key of the file a published URI names
+    pub fn from_uri(uri: &str) -> Self {
+        match uri_to_path(uri) {
+            Ok(path) => Self::from_path(&path),
+            Err(_) => Self::Uri(normalize_uri(uri)),
+        }
+    }
+
+    /// Key of a local file; `.` components and doubled separators are
+    /// dropped so they don't make a second key
+    pub fn from_path(path: &Path) -> Self {
+        Self::Path(path.components().collect())
+    }
+
+    /// Path of a local file
+    pub fn path(&self) -> Option<&Path> {
+        match self {
+            Self::Path(path) => Some(path),
+            Self::Uri(_) => None,
+        }
+    }
+}
+
+impl fmt::Display for DiagnosticFile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Path(path) => write!(f, "{}", path.display()),
+            Self::Uri(uri) => f.write_str(uri),
+        }
+    }
+}
+
+/// Number of diagnostics of each severity
+///
+/// Diagnostics without a severity count as hints, as they are shown.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SeverityCounts {
+    pub errors: usize,
+    pub warnings: usize,
+    pub infos: usize,
+    pub hints: usize,
+}
+
+impl SeverityCounts {
+    /// Counts of `diagnostics`
+    pub fn of(diagnostics: &[Diagnostic]) -> Self {
+        let mut counts = Self::default();
+        for diagnostic in diagnostics {
+            match diagnostic.severity {
+                Some(DiagnosticSeverity::ERROR) => counts.errors += 1,
+                Some(DiagnosticSeverity::WARNING) => counts.warnings += 1,
+                Some(DiagnosticSeverity::INFORMATION) => counts.infos += 1,
+                _ => counts.hints += 1,
+            }
+        }
+        counts
+    }
+
+    pub fn total(&self) -> usize {
+        self.errors + self.warnings + self.infos + self.hints
+    }
+
+    fn add(&mut self, other: &Self) {
+        self.errors += other.errors;
+        self.warnings += other.warnings;
+        self.infos += other.infos;
+        self.hints += other.hints;
+    }
+
+    fn sub(&mut self, other: &Self) {
+        self.errors -= other.errors;
+        self.warnings -= other.warnings;
+        self.infos -= other.infos;
+        self.hints -= other.hints;
+    }
+
+    /// Ordering key putting files with the most errors, then warnings, first
+    pub fn rank(&self) -> impl Ord {
+        (
+            Reverse(self.errors),
+            Reverse(self.warnings),
+            Reverse(self.infos + self.hints),
+        )
+    }
+}
+
+/// Diagnostics last published for one file
+#[derive(Debug, Clone)]
+pub struct FileDiagnostics {
+    /// Canonical URI of the file
+    pub uri: String,
+    pub diagnostics: Vec<Diagnostic>,
+    pub counts: SeverityCounts,
+}
+
+/// Counts of one file in a [`DiagnosticsSummary`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileDiagnosticCounts {
+    pub file: DiagnosticFile,
+    /// Canonical URI of the file
+    pub uri: String,
+    pub counts: SeverityCounts,
+}
+
+/// Counts of the workspace and of each file with diagnostics
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DiagnosticsSummary {
+    pub total: SeverityCounts,
+    /// Files with the most errors, then warnings, first
+    pub files: Vec<FileDiagnosticCounts>,
+}
+
+/// Diagnostics of every file a language server published for
+#[derive(Debug, Clone, Default)]
+pub struct DiagnosticStore {
+    files: HashMap<DiagnosticFile, FileDiagnostics>,
+    /// What each server last published for a file, by server label
+    by_server: HashMap<DiagnosticFile, BTreeMap<String, Vec<Diagnostic>>>,
+    total: SeverityCounts,
+}
+
+impl DiagnosticStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Replace the diagnostics of the file `uri` names; an empty list
+    /// removes its entry
+    ///
+    /// Returns the key the diagnostics were stored under.
+    pub fn publish(&mut self, uri: &str, diagnostics: Vec<Diagnostic>) -> DiagnosticFile {
+        let file = DiagnosticFile::from_uri(uri);
+        if let Some(previous) = self.files.remove(&file) {
+            self.total.sub(&previous.counts);
+        }
+        if !diagnostics.is_empty() {
+            let counts = SeverityCounts::of(&diagnostics);
+            self.total.add(&counts);
+            let uri = match &file {
+                DiagnosticFile::Path(path) => path_to_uri(path)
+                    .map(|uri| uri.as_str().to_string())
+                    .unwrap_or_else(|| normalize_uri(uri)),
+                DiagnosticFile::Uri(uri) => uri.clone(),
+            };
+            self.files.insert(
+                file.clone(),
+                FileDiagnostics {
+                    uri,
+                    diagnostics,
+                    counts,
+                },
+            );
+        }
+        file
+    }
+
+    /// Replace the diagnostics the server labelled `server` published for
+    /// the file `uri` names, keeping those of the file's other servers
+    ///
+    /// The file's diagnostics are those of all its servers, in the order of
+    /// their labels. Returns the key the diagnostics were stored under.
+    pub fn publish_from(
+        &mut self,
+        server: &str,
+        uri: &str,
+        diagnostics: Vec<Diagnostic>,
+    ) -> DiagnosticFile {
+        let file = DiagnosticFile::from_uri(uri);
+        let servers = self.by_server.entry(file.clone()).or_default();
+        if diagnostics.is_empty() {
+            servers.remove(server);
+        } else {
+            servers.insert(server.to_string(), diagnostics);
+        }
+        let all: Vec<Diagnostic> = servers.values().flatten().cloned().collect();
+        if servers.is_empty() {
+            self.by_server.remove(&file);
+        }
+        self.publish(uri, all)
+    }
+
+    /// Diagnostics of the file `uri` names, however it is spelled
+    pub fn get(&self, uri: &str) -> Option<&FileDiagnostics> {
+        self.files.get(&DiagnosticFile::from_uri(uri))
+    }
+
+    /// Diagnostics of a file by its key
+    pub fn get_file(&self, file: &DiagnosticFile) -> Option<&FileDiagnostics> {
+        self.files.get(file)
+    }
+
+    /// Diagnostics of a local file
+    pub fn for_path(&self, path: &Path) -> Option<&FileDiagnostics> {
+        self.files.get(&DiagnosticFile::from_path(path))
+    }
+
+    /// Counts of a local file; zero when it has no diagnostics
+    pub fn counts_for_path(&self, path: &Path) -> SeverityCounts {
+        self.for_path(path)
+            .map(|file| file.counts)
+            .unwrap_or_default()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&DiagnosticFile, &FileDiagnostics)> {
+        self.files.iter()
+    }
+
+    /// Number of files with diagnostics
+    pub fn len(&self) -> usize {
+        self.files.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    /// Counts of the whole workspace
+    pub fn total(&self) -> SeverityCounts {
+        self.total
+    }
+
+    /// Counts of the workspace and of each file
+    pub fn summary(&self) -> DiagnosticsSummary {
+        let mut files: Vec<FileDiagnosticCounts> = self
+            .files
+            .iter()
+            .map(|(file, entry)| FileDiagnosticCounts {
+                file: file.clone(),
+                uri: entry.uri.clone(),
+                counts: entry.counts,
+            })
+            .collect();
+        files.sort_by(|a, b| (a.counts.rank(), &a.file).cmp(&(b.counts.rank(), &b.file)));
+        DiagnosticsSummary {
+            total: self.total,
+            files,
+        }
+    }
+
+    /// Diagnostics keyed by canonical URI, as plugins see them
+    pub fn by_uri(&self) -> HashMap<String, Vec<Diagnostic>> {
+        self.files
+            .values()
+            .map(|entry| (entry.uri.clone(), entry.diagnostics.clone()))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lsp_types::{Position, Range};
+
+    fn diagnostic(severity: Option<DiagnosticSeverity>, message: &str) -> Diagnostic {
+        Diagnostic {
+            range: Range::new(Position::new(0, 0), Position::new(0, 1)),
+            severity,
+            message: message.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_spellings_of_one_file_share_an_entry() {
+        let mut store = DiagnosticStore::new();
+        store.publish(
+            "file:///src/my%20file.rs",
+            vec![diagnostic(Some(DiagnosticSeverity::ERROR), "first")],
+        );
+        store.publish(
+            "file:///src/./my file.rs",
+            vec![
+                diagnostic(Some(DiagnosticSeverity::WARNING), "second"),
+                diagnostic(None, "third"),
+            ],
+        );
+
+        assert_eq!(store.len(), 1);
+        let entry = store.get("file://localhost/src/my%20file.rs").unwrap();
+        assert_eq!(entry.uri, "file:///src/my%20file.rs");
+        assert_eq!(entry.diagnostics[0].message, "second");
+        let expected = SeverityCounts {
+            warnings: 1,
+            hints: 1,
+            ..Default::default()
+        };
+        assert_eq!(entry.counts, expected);
+        assert_eq!(store.total(), expected);
+        assert!(store.for_path(Path::new("/src/my file.rs")).is_some());
+    }
+
+    #[test]
+    fn test_other_schemes_keep_their_uri() {
+        let mut store = DiagnosticStore::new();
+        let file = store.publish(
+            "untitled:Untitled-1",
+            vec![diagnostic(Some(DiagnosticSeverity::INFORMATION), "note")],
+        );
+        assert_eq!(file, DiagnosticFile::Uri("untitled:Untitled-1".to_string()));
+        assert_eq!(store.get("untitled:Untitled-1").unwrap().counts.infos, 1);
+        assert_eq!(file.to_string(), "untitled:Untitled-1");
+    }
+
+    #[test]
+    fn test_summary_totals_and_order() {
+        let mut store = DiagnosticStore::new();
+        let error = || diagnostic(Some(DiagnosticSeverity::ERROR), "e");
+        let warning = || diagnostic(Some(DiagnosticSeverity::WARNING), "w");
+        store.publish("file:///b.rs", vec![warning(), warning()]);
+        store.publish("file:///c.rs", vec![error(), warning()]);
+        store.publish("file:///a.rs", vec![warning(), warning()]);
+        store.publish("file:///d.rs", vec![error()]);
+
+        let summary = store.summary();
+        let order: Vec<String> = summary.files.iter().map(|f| f.uri.clone()).collect();
+        assert_eq!(
+            order,
+            [
+                "file:///c.rs",
+                "file:///d.rs",
+                "file:///a.rs",
+                "file:///b.rs"
+            ]
+        );
+        assert_eq!(summary.total.errors, 2);
+        assert_eq!(summary.total.warnings, 5);
+
+        // Publishing nothing removes the file and its counts
+        store.publish("file:///c.rs", Vec::new());
+        assert_eq!(store.len(), 3);
+        assert_eq!(store.total().errors, 1);
+        assert_eq!(store.total().warnings, 4);
+    }
+
+    #[test]
+    fn test_servers_of_one_file_are_concatenated() {
+        let mut store = DiagnosticStore::new();
+        let messages = |store: &DiagnosticStore| -> Vec<String> {
+            store.get("file:///a.py").map_or_else(Vec::new, |entry| {
+                entry
+                    .diagnostics
+                    .iter()
+                    .map(|d| d.message.clone())
+                    .collect()
+            })
+        };
+        store.publish_from(
+            "python/ruff",
+            "file:///a.py",
+            vec![diagnostic(Some(DiagnosticSeverity::WARNING), "unused")],
+        );
+        store.publish_from(
+            "python/pyright",
+            "file:///a.py",
+            vec![diagnostic(Some(DiagnosticSeverity::ERROR), "type")],
+        );
+        assert_eq!(messages(&store), ["type", "unused"]);
+        assert_eq!(store.total().errors, 1);
+        assert_eq!(store.total().warnings, 1);
+
+        // A server clearing its diagnostics leaves the other's
+        store.publish_from("python/pyright", "file:///a.py", Vec::new());
+        assert_eq!(messages(&store), ["unused"]);
+        store.publish_from("python/ruff", "file:///a.py", Vec::new());
+        assert!(store.is_empty());
+        assert_eq!(store.total().total(), 0);
+    }
+}
@@ -3,6 +3,7 @@
 ///! This module handles converting LSP diagnostics to visual overlays in the editor.
 ///! Diagnostics are displayed as colored underlines (red for errors, yellow for warnings, etc.)
 use crate::model::buffer::Buffer;
+use crate::model::position_encoding::PositionEncoding;
 use crate::state::EditorState;
 use crate::view::overlay::{Overlay, OverlayFace, OverlayNamespace};
 use lsp_types::{Diagnostic, DiagnosticSeverity};
@@ -20,10 +21,11 @@ pub fn lsp_diagnostic_namespace() -> OverlayNamespace {
 /// Using a global static with Mutex for simplicity - could be moved to EditorState later
 static DIAGNOSTIC_CACHE: Mutex<Option<u64>> = Mutex::new(None);
 
-/// Compute a hash for a slice of diagnostics
+/// Compute a hash for a slice of diagnostics and the units of their positions
 /// This hash is used to quickly detect if diagnostics have changed
-fn compute_diagnostic_hash(diagnostics: &[Diagnostic]) -> u64 {
+fn compute_diagnostic_hash(diagnostics: &[Diagnostic], encoding: PositionEncoding) -> u64 {
     let mut hasher = DefaultHasher::new();
+    encoding.hash(&mut hasher);
 
     // Hash the count first
     diagnostics.len().hash(&mut hasher);
@@ -66,10 +68,11 @@ fn compute_diagnostic_hash(diagnostics: &[Diagnostic]) -> u64 {
 pub fn apply_diagnostics_to_state_cached(
     state: &mut EditorState,
     diagnostics: &[Diagnostic],
+    encoding: PositionEncoding,
     theme: &crate::view::theme::Theme,
 ) {
     // Compute hash of incoming diagnostics
-    let new_hash = compute_diagnostic_hash(diagnostics);
+    let new_hash = compute_diagnostic_hash(diagnostics, encoding);
 
     // Check if this is the same as last time
     if let Ok(cache) = DIAGNOSTIC_CACHE.lock() {
@@ -82,7 +85,7 @@ pub fn apply_diagnostics_to_state_cached(
     }
 
     // Diagnostics have changed, do the expensive update
-    apply_diagnostics_to_state(state, diagnostics, theme);
+    apply_diagnostics_to_state(state, diagnostics, encoding, theme);
 
     // Update cache
     if let Ok(mut cache) = DIAGNOSTIC_CACHE.lock() {
@@ -95,19 +98,18 @@ pub fn apply_diagnostics_to_state_cached(
 pub fn diagnostic_to_overlay(
     diagnostic: &Diagnostic,
     buffer: &Buffer,
+    encoding: PositionEncoding,
     theme: &crate::view::theme::Theme,
 ) -> Option<(Range<usize>, OverlayFace, i32)> {
-    // Convert LSP positions (line/character) to byte offsets
-    // LSP uses 0-indexed lines and characters (UTF-16 code units)
+    // LSP uses 0-indexed lines and characters (in the server's position encoding)
     let start_line = diagnostic.range.start.line as usize;
     let start_char = diagnostic.range.start.character as usize;
     let end_line = diagnostic.range.end.line as usize;
     let end_char = diagnostic.range.end.character as usize;
 
-    // Convert LSP positions (line/UTF-16 character) to byte positions
-    // LSP uses UTF-16 code units for character offsets
-    let start_byte = buffer.lsp_position_to_byte(start_line, start_char);
-    let end_byte = buffer.lsp_position_to_byte(end_line, end_char);
+    // Convert LSP positions (line/character offset) to byte positions
+    let start_byte = buffer.lsp_position_to_byte(start_line, start_char, encoding);
+    let end_byte = buffer.lsp_position_to_byte(end_line, end_char, encoding);
 
     // Determine overlay face based on diagnostic severity using theme colors
     let (face, priority) = match diagnostic.severity {
@@ -149,6 +151,7 @@ pub fn diagnostic_to_overlay(
 pub fn apply_diagnostics_to_state(
     state: &mut EditorState,
     diagnostics: &[Diagnostic],
+    encoding: PositionEncoding,
     theme: &crate::view::theme::Theme,
 ) {
     let ns = lsp_diagnostic_namespace();
@@ -160,7 +163,7 @@ pub fn apply_diagnostics_to_state(
     let mut added_count = 0;
     for diagnostic in diagnostics {
         if let Some((range, face, priority)) =
-            diagnostic_to_overlay(diagnostic, &state.buffer, theme)
+            diagnostic_to_overlay(diagnostic, &state.buffer, encoding, theme)
         {
             let message = diagnostic.message.clone();
 
@@ -168,7 +171,7 @@ pub fn apply_diagnostics_to_state(
                 .with_priority_value(priority)
                 .with_message(message);
 
-            state.overlays.add(overlay);
+            state.overlays.add(overlay, &state.marker_list);
             added_count += 1;
         }
     }
@@ -189,22 +192,40 @@ mod tests {
         let buffer = Buffer::from_str_test("hello\nworld\ntest");
 
         // Line 0, character 0
-        assert_eq!(buffer.lsp_position_to_byte(0, 0), 0);
+        assert_eq!(
+            buffer.lsp_position_to_byte(0, 0, PositionEncoding::Utf16),
+            0
+        );
 
         // Line 0, character 5 (end of "hello")
-        assert_eq!(buffer.lsp_position_to_byte(0, 5), 5);
+        assert_eq!(
+            buffer.lsp_position_to_byte(0, 5, PositionEncoding::Utf16),
+            5
+        );
 
         // Line 1, character 0 (start of "world")
-        assert_eq!(buffer.lsp_position_to_byte(1, 0), 6);
+        assert_eq!(
+            buffer.lsp_position_to_byte(1, 0, PositionEncoding::Utf16),
+            6
+        );
 
         // Line 1, character 5 (end of "world")
-        assert_eq!(buffer.lsp_position_to_byte(1, 5), 11);
+        assert_eq!(
+            buffer.lsp_position_to_byte(1, 5, PositionEncoding::Utf16),
+            11
+        );
 
         // Line 2, character 0 (start of "test")
-        assert_eq!(buffer.lsp_position_to_byte(2, 0), 12);
+        assert_eq!(
+            buffer.lsp_position_to_byte(2, 0, PositionEncoding::Utf16),
+            12
+        );
 
         // Out of bounds line - should clamp to end of buffer
-        assert_eq!(buffer.lsp_position_to_byte(10, 0), buffer.len());
+        assert_eq!(
+            buffer.lsp_position_to_byte(10, 0, PositionEncoding::Utf16),
+            buffer.len()
+        );
     }
 
     #[test]
@@ -233,7 +254,7 @@ mod tests {
         };
 
         let theme = crate::view::theme::Theme::dark();
-        let result = diagnostic_to_overlay(&diagnostic, &buffer, &theme);
+        let result = diagnostic_to_overlay(&diagnostic, &buffer, PositionEncoding::Utf16, &theme);
         assert!(result.is_some());
 
         let (range, face, priority) = result.unwrap();
@@ -274,7 +295,7 @@ mod tests {
         };
 
         let theme = crate::view::theme::Theme::dark();
-        let result = diagnostic_to_overlay(&diagnostic, &buffer, &theme);
+        let result = diagnostic_to_overlay(&diagnostic, &buffer, PositionEncoding::Utf16, &theme);
         assert!(result.is_some());
 
         let (range, face, priority) = result.unwrap();
@@ -315,7 +336,7 @@ mod tests {
         };
 
         let theme = crate::view::theme::Theme::dark();
-        let result = diagnostic_to_overlay(&diagnostic, &buffer, &theme);
+        let result = diagnostic_to_overlay(&diagnostic, &buffer, PositionEncoding::Utf16, &theme);
         assert!(result.is_some());
 
         let (range, _, _) = result.unwrap();
@@ -0,0 +1,263 @@
+//! How a language server takes positions and document changes
+//!
+//! The client offers UTF-8 and UTF-16 positions; the server's initialize
+//! result names the one it picked (`positionEncoding`, UTF-16 if absent) and
+//! how it wants to hear of edits (`textDocumentSync`): not at all, as the
+//! whole text, or as the edited ranges. The editor keeps both per server and
+//! builds every position and `didChange` from them.
+
+use std::ops::Range;
+
+use lsp_types::{
+    Position, PositionEncodingKind, Range as LspRange, ServerCapabilities,
+    TextDocumentContentChangeEvent, TextDocumentSyncCapability, TextDocumentSyncKind,
+};
+
+use crate::model::buffer::Buffer;
+use crate::model::position_encoding::PositionEncoding;
+
+/// How a server wants to hear of document changes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DocumentSync {
+    /// Not at all
+    None,
+    /// The whole text after each change
+    Full,
+    /// The edited ranges and their new text
+    Incremental,
+}
+
+impl DocumentSync {
+    /// The protocol's name of the sync kind
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::None => "none",
+            Self::Full => "full",
+            Self::Incremental => "incremental",
+        }
+    }
+}
+
+/// What a server agreed to at initialization
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ServerSync {
+    pub encoding: PositionEncoding,
+    pub sync: DocumentSync,
+}
+
+impl ServerSync {
+    /// Read the position encoding and sync kind from a server's capabilities,
+    /// with the protocol's defaults for what it leaves out
+    pub fn from_capabilities(capabilities: &ServerCapabilities) -> Self {
+        let encoding = capabilities
+            .position_encoding
+            .as_ref()
+            .and_then(|kind| PositionEncoding::from_name(kind.as_str()))
+            .unwrap_or_default();
+        let kind = match &capabilities.text_document_sync {
+            Some(TextDocumentSyncCapability::Kind(kind)) => Some(*kind),
+            Some(TextDocumentSyncCapability::Options(options)) => options.change,
+            None => None,
+        };
+        let sync = match kind {
+            Some(TextDocumentSyncKind::INCREMENTAL) => DocumentSync::Incremental,
+            Some(TextDocumentSyncKind::FULL) => DocumentSync::Full,
+            _ => DocumentSync::None,
+        };
+        Self { encoding, sync }
+    }
+}
+
+/// The position encodings the client offers, preferred first
+pub fn client_position_encodings() -> Vec<PositionEncodingKind> {
+    vec![PositionEncodingKind::UTF8, PositionEncodingKind::UTF16]
+}
+
+/// The position encodings offered to servers sharing a language's
+/// documents, so one set of positions serves all of them
+pub fn shared_position_encodings() -> Vec<PositionEncodingKind> {
+    vec![PositionEncodingKind::UTF16]
+}
+
+/// The changes of one edit to a document
+#[derive(Debug, Clone, PartialEq)]
+pub enum DocumentChanges {
+    /// Edited ranges, each applying to the text the ones before it left;
+    /// empty if no text changed
+    Ranges(Vec<TextDocumentContentChangeEvent>),
+    /// Edits only the whole new text describes
+    Whole,
+}
+
+/// The changes replacing each byte range of `buffer` with its text, in the
+/// order the edits are made, with positions in `encoding`
+///
+/// Positions are read from the buffer before any of the edits, which holds
+/// while each edit ends at or before the start of the one before it; edits
+/// in any other order take the whole text.
+pub fn range_changes<'a>(
+    buffer: &Buffer,
+    edits: impl IntoIterator<Item = (Range<usize>, &'a str)>,
+    encoding: PositionEncoding,
+) -> DocumentChanges {
+    let mut changes = Vec::new();
+    // Where the text the edits so far changed starts
+    let mut changed_from = usize::MAX;
+    for (range, text) in edits {
+        if range.end > changed_from {
+            return DocumentChanges::Whole;
+        }
+        changed_from = range.start;
+        let (start_line, start_char) = buffer.position_to_lsp_position(range.start, encoding);
+        let start = Position::new(start_line as u32, start_char as u32);
+        let end = if range.is_empty() {
+            start
+        } else {
+            let (end_line, end_char) = buffer.position_to_lsp_position(range.end, encoding);
+            Position::new(end_line as u32, end_char as u32)
+        };
+        changes.push(TextDocumentContentChangeEvent {
+            range: Some(LspRange::new(start, end)),
+            range_length: None,
+            text: text.to_string(),
+        });
+    }
+    DocumentChanges::Ranges(changes)
+}
+
+/// The change replacing a document's whole text
+pub fn whole_text_change(text: String) -> TextDocumentContentChangeEvent {
+    TextDocumentContentChangeEvent {
+        range: None,
+        range_length: None,
+        text,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lsp_types::TextDocumentSyncOptions;
+
+    fn change(start: (u32, u32), end: (u32, u32), text: &str) -> TextDocumentContentChangeEvent {
+        TextDocumentContentChangeEvent {
+            range: Some(LspRange::new(
+                Position::new(start.0, start.1),
+                Position::new(end.0, end.1),
+            )),
+            range_length: None,
+            text: text.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_server_sync_from_capabilities() {
+        let defaults = ServerSync::from_capabilities(&ServerCapabilities::default());
+        assert_eq!(defaults.encoding, PositionEncoding::Utf16);
+        assert_eq!(defaults.sync, DocumentSync::None);
+
+        let capabilities = ServerCapabilities {
+            position_encoding: Some(PositionEncodingKind::UTF8),
+            text_document_sync: Some(TextDocumentSyncCapability::Kind(
+                TextDocumentSyncKind::INCREMENTAL,
+            )),
+            ..Default::default()
+        };
+        assert_eq!(
+            ServerSync::from_capabilities(&capabilities),
+            ServerSync {
+                encoding: PositionEncoding::Utf8,
+                sync: DocumentSync::Incremental,
+            }
+        );
+
+        let capabilities = ServerCapabilities {
+            position_encoding: Some(PositionEncodingKind::UTF32),
+            text_document_sync: Some(TextDocumentSyncCapability::Options(
+                TextDocumentSyncOptions {
+                    open_close: Some(true),
+                    change: Some(TextDocumentSyncKind::FULL),
+                    ..Default::default()
+                },
+            )),
+            ..Default::default()
+        };
+        assert_eq!(
+            ServerSync::from_capabilities(&capabilities),
+            ServerSync {
+                encoding: PositionEncoding::Utf32,
+                sync: DocumentSync::Full,
+            }
+        );
+
+        // An encoding the client never offered is read as the default
+        let capabilities = ServerCapabilities {
+            position_encoding: Some(PositionEncodingKind::new("utf-7")),
+            ..Default::default()
+        };
+        assert_eq!(
+            ServerSync::from_capabilities(&capabilities).encoding,
+            PositionEncoding::Utf16
+        );
+    }
+
+    #[test]
+    fn test_range_changes_in_each_encoding() {
+        // "😀" is 4 bytes, a UTF-16 surrogate pair and one code point
+        let buffer = Buffer::from_str_test("a😀b\n😀x\n");
+        let after_b = 1 + 4 + 1;
+        let x = after_b + 1 + 4;
+        let edits = || [(x..x + 1, "y"), (after_b..after_b, "!")];
+
+        let cases = [
+            (PositionEncoding::Utf8, 6, 4),
+            (PositionEncoding::Utf16, 4, 2),
+            (PositionEncoding::Utf32, 3, 1),
+        ];
+        for (encoding, after_b_char, x_char) in cases {
+            assert_eq!(
+                range_changes(&buffer, edits(), encoding),
+                DocumentChanges::Ranges(vec![
+                    change((1, x_char), (1, x_char + 1), "y"),
+                    change((0, after_b_char), (0, after_b_char), "!"),
+                ]),
+                "{:?}",
+                encoding
+            );
+        }
+    }
+
+    #[test]
+    fn test_replacement_at_one_place_keeps_its_order() {
+        let buffer = Buffer::from_str_test("let val = 1;\n");
+        assert_eq!(
+            range_changes(
+                &buffer,
+                [(4..7, ""), (4..4, "value")],
+                PositionEncoding::Utf16
+            ),
+            DocumentChanges::Ranges(vec![
+                change((0, 4), (0, 7), ""),
+                change((0, 4), (0, 4), "value"),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_edits_after_earlier_ones_take_the_whole_text() {
+        let buffer = Buffer::from_str_test("one\ntwo\n");
+        assert_eq!(
+            range_changes(&buffer, [(0..0, "a"), (4..4, "b")], PositionEncoding::Utf16),
+            DocumentChanges::Whole
+        );
+        // Overlapping the text an edit before changed
+        assert_eq!(
+            range_changes(&buffer, [(4..5, ""), (2..5, "")], PositionEncoding::Utf16),
+            DocumentChanges::Whole
+        );
+        assert_eq!(
+            range_changes(&buffer, [], PositionEncoding::Utf16),
+            DocumentChanges::Ranges(Vec::new())
+        );
+    }
+}
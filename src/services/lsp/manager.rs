@@ -1,14 +1,20 @@
 //! LSP Manager - manages multiple language servers using async I/O
 //!
 //! This module provides a manager for multiple LSP servers that:
-//! - Spawns one server per language
+//! - Spawns the servers configured for each language, one or several
 //! - Uses async LspHandle for non-blocking I/O
 //! - Routes requests to appropriate servers
 //! - Configured via config.json
+//!
+//! Each server goes by a label in statuses, messages and commands: its
+//! language when it is the language's only server, else `language/name`.
+//! Restarts, cooldowns and stops are tracked per label.
 
 use crate::services::async_bridge::AsyncBridge;
-use crate::services::lsp::async_handler::LspHandle;
-use crate::types::LspServerConfig;
+use crate::services::command_trust::{CommandGate, CommandRequest};
+use crate::services::lsp::async_handler::{LspHandle, GRACEFUL_SHUTDOWN_TIMEOUT};
+use crate::services::lsp::document_sync::{client_position_encodings, shared_position_encodings};
+use crate::types::LspLanguageConfig;
 use lsp_types::Uri;
 use std::collections::{HashMap, HashSet};
 use std::time::{Duration, Instant};
@@ -30,13 +36,32 @@ const MAX_RESTARTS_IN_WINDOW: usize = 5;
 const RESTART_WINDOW_SECS: u64 = 180; // 3 minutes
 const RESTART_BACKOFF_BASE_MS: u64 = 1000; // 1s, 2s, 4s, 8s...
 
+/// Language a server label belongs to
+pub fn label_language(label: &str) -> &str {
+    label
+        .split_once('/')
+        .map_or(label, |(language, _)| language)
+}
+
+/// Name of the server a label picks out of its language's servers, if it
+/// names one
+pub fn label_server(label: &str) -> Option<&str> {
+    label.split_once('/').map(|(_, name)| name)
+}
+
+/// A running server of a language
+struct RunningServer {
+    label: String,
+    handle: LspHandle,
+}
+
 /// Manager for multiple language servers (async version)
 pub struct LspManager {
-    /// Map from language ID to LSP handle
-    handles: HashMap<String, LspHandle>,
+    /// Running servers of each language, primary first
+    handles: HashMap<String, Vec<RunningServer>>,
 
     /// Configuration for each language
-    config: HashMap<String, LspServerConfig>,
+    config: HashMap<String, LspLanguageConfig>,
 
     /// Root URI for workspace
     root_uri: Option<Uri>,
@@ -47,22 +72,25 @@ pub struct LspManager {
     /// Async bridge for communication
     async_bridge: Option<AsyncBridge>,
 
-    /// Restart attempt timestamps per language (for tracking restart frequency)
+    /// Restart attempt timestamps per server label (for tracking restart frequency)
     restart_attempts: HashMap<String, Vec<Instant>>,
 
-    /// Languages currently in restart cooldown (gave up after too many restarts)
+    /// Servers currently in restart cooldown (gave up after too many restarts)
     restart_cooldown: HashSet<String>,
 
-    /// Scheduled restart times (language -> when to restart)
+    /// Scheduled restart times (server label -> when to restart)
     pending_restarts: HashMap<String, Instant>,
 
     /// Languages that have been manually started by the user
     /// If a language is in this set, it will spawn even if auto_start=false in config
     allowed_languages: HashSet<String>,
 
-    /// Languages that have been explicitly disabled/stopped by the user
+    /// Languages or server labels that have been explicitly disabled/stopped by the user
     /// These will not auto-restart until user manually restarts them
     disabled_languages: HashSet<String>,
+
+    /// Asked before spawning a server named by the project's config
+    command_gate: Option<CommandGate>,
 }
 
 impl LspManager {
@@ -79,6 +107,7 @@ impl LspManager {
             pending_restarts: HashMap::new(),
             allowed_languages: HashSet::new(),
             disabled_languages: HashSet::new(),
+            command_gate: None,
         }
     }
 
@@ -99,10 +128,28 @@ impl LspManager {
     }
 
     /// Get the configuration for a specific language
-    pub fn get_config(&self, language: &str) -> Option<&LspServerConfig> {
+    pub fn get_config(&self, language: &str) -> Option<&LspLanguageConfig> {
         self.config.get(language)
     }
 
+    /// Labels of the servers a language's buffers are attached to, primary
+    /// first: its enabled servers that weren't stopped
+    pub fn server_labels(&self, language: &str) -> Vec<String> {
+        if self.disabled_languages.contains(language) {
+            return Vec::new();
+        }
+        let Some(config) = self.config.get(language) else {
+            return Vec::new();
+        };
+        config
+            .ranked()
+            .into_iter()
+            .filter(|server| server.enabled)
+            .map(|server| config.label(language, server))
+            .filter(|label| !self.disabled_languages.contains(label))
+            .collect()
+    }
+
     /// Try to spawn an LSP server, checking auto_start configuration
     ///
     /// This is the main entry point for spawning LSP servers on file open.
@@ -110,15 +157,21 @@ impl LspManager {
     /// - `LspSpawnResult::Spawned` if the server was spawned or already running
     /// - `LspSpawnResult::NotAutoStart` if auto_start is false and not manually allowed
     /// - `LspSpawnResult::Failed` if spawn failed or language is disabled
+    ///
+    /// A language with several servers auto-starts if any of them does.
     pub fn try_spawn(&mut self, language: &str) -> LspSpawnResult {
-        // If handle already exists, return success
-        if self.handles.contains_key(language) {
+        // If a server already runs, start any others that should
+        if self.handles.get(language).is_some_and(|s| !s.is_empty()) {
+            self.get_or_spawn(language);
             return LspSpawnResult::Spawned;
         }
 
         // Check if language is configured and enabled
-        let config = match self.config.get(language) {
-            Some(c) if c.enabled => c,
+        let auto_start = match self.config.get(language) {
+            Some(c) if c.any_enabled() => c
+                .servers()
+                .iter()
+                .any(|server| server.enabled && server.auto_start),
             Some(_) => return LspSpawnResult::Failed, // Disabled
             None => return LspSpawnResult::Failed,    // Not configured
         };
@@ -129,7 +182,7 @@ impl LspManager {
         }
 
         // Check if auto_start is enabled or language was manually allowed
-        if !config.auto_start && !self.allowed_languages.contains(language) {
+        if !auto_start && !self.allowed_languages.contains(language) {
             return LspSpawnResult::NotAutoStart;
         }
 
@@ -149,8 +202,13 @@ impl LspManager {
         self.async_bridge = Some(async_bridge);
     }
 
+    /// Set the gate that project-supplied server commands must pass
+    pub fn set_command_gate(&mut self, gate: CommandGate) {
+        self.command_gate = Some(gate);
+    }
+
     /// Set configuration for a language
-    pub fn set_language_config(&mut self, language: String, config: LspServerConfig) {
+    pub fn set_language_config(&mut self, language: String, config: LspLanguageConfig) {
         self.config.insert(language, config);
     }
 
@@ -187,13 +245,54 @@ impl LspManager {
         );
     }
 
-    /// Get or spawn an LSP handle for a language
+    /// Get or spawn the LSP handle answering a language's requests
+    ///
+    /// Every server of the language is spawned if it isn't running; the
+    /// handle returned is the primary server's, or the first running one's
+    /// if the primary couldn't be started.
     pub fn get_or_spawn(&mut self, language: &str) -> Option<&mut LspHandle> {
-        // Return existing handle if available
-        if self.handles.contains_key(language) {
-            return self.handles.get_mut(language);
-        }
+        self.spawn_missing(language);
+        let servers = self.handles.get_mut(language)?;
+        servers.first_mut().map(|server| &mut server.handle)
+    }
+
+    /// Get or spawn every server of a language, primary first, with their
+    /// labels
+    pub fn get_or_spawn_all(&mut self, language: &str) -> Vec<(&str, &LspHandle)> {
+        self.spawn_missing(language);
+        self.handles
+            .get(language)
+            .map(|servers| {
+                servers
+                    .iter()
+                    .map(|server| (server.label.as_str(), &server.handle))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Get or spawn the server with label `label`
+    pub fn get_or_spawn_server(&mut self, label: &str) -> Option<&mut LspHandle> {
+        let language = label_language(label);
+        self.spawn_missing(language);
+        self.handles
+            .get_mut(language)?
+            .iter_mut()
+            .find(|server| server.label == label)
+            .map(|server| &mut server.handle)
+    }
+
+    /// The running server with label `label`, without spawning it
+    pub fn server(&self, label: &str) -> Option<&LspHandle> {
+        self.handles
+            .get(label_language(label))?
+            .iter()
+            .find(|server| server.label == label)
+            .map(|server| &server.handle)
+    }
 
+    /// Spawn the servers of a language that should run and don't
+    fn spawn_missing(&mut self, language: &str) {
         // Check if language was explicitly disabled by user (via stop command)
         // Don't auto-spawn disabled languages
         if self.disabled_languages.contains(language) {
@@ -201,102 +300,186 @@ impl LspManager {
                 "LSP for {} is disabled, not spawning (use manual restart to re-enable)",
                 language
             );
-            return None;
+            return;
         }
 
         // Get config for this language
-        let config = self.config.get(language)?;
-
-        if !config.enabled {
-            return None;
-        }
-
-        // Check we have runtime and bridge
-        let runtime = self.runtime.as_ref()?;
-        let async_bridge = self.async_bridge.as_ref()?;
-
-        // Spawn new handle
-        tracing::info!("Spawning async LSP server for language: {}", language);
-
-        match LspHandle::spawn(
-            runtime,
-            &config.command,
-            &config.args,
-            language.to_string(),
-            async_bridge,
-            config.process_limits.clone(),
-        ) {
-            Ok(handle) => {
-                // Initialize the handle (non-blocking)
-                // The handle will become ready asynchronously
-                if let Err(e) =
-                    handle.initialize(self.root_uri.clone(), config.initialization_options.clone())
-                {
-                    tracing::error!("Failed to send initialize command for {}: {}", language, e);
-                    return None;
+        let Some(config) = self.config.get(language) else {
+            return;
+        };
+        let labels = self.server_labels(language);
+        let running = self.handles.get(language);
+        let missing: Vec<String> = labels
+            .iter()
+            .filter(|label| !running.is_some_and(|s| s.iter().any(|s| &s.label == *label)))
+            .cloned()
+            .collect();
+        if missing.is_empty() {
+            return;
+        }
+        let shares_documents = config.servers().len() > 1;
+
+        let mut spawned = Vec::new();
+        for server in config.ranked() {
+            let label = config.label(language, server);
+            if !missing.contains(&label) {
+                continue;
+            }
+
+            // Servers named by the project's config wait for the user's approval
+            if let Some(gate) = &self.command_gate {
+                if !gate.authorize(&CommandRequest::lsp_server(language, server)) {
+                    continue;
+                }
+            }
+
+            // Check we have runtime and bridge
+            let (Some(runtime), Some(async_bridge)) = (&self.runtime, &self.async_bridge) else {
+                return;
+            };
+
+            // Spawn new handle
+            tracing::info!("Spawning async LSP server {}", label);
+
+            let handle = match LspHandle::spawn(
+                runtime,
+                &server.command,
+                &server.args,
+                label.clone(),
+                async_bridge,
+                server.process_limits.clone(),
+            ) {
+                Ok(handle) => handle,
+                Err(e) => {
+                    tracing::error!("Failed to spawn LSP handle for {}: {}", label, e);
+                    continue;
                 }
+            };
 
-                tracing::info!(
-                    "LSP initialization started for {}, will be ready asynchronously",
-                    language
-                );
-                self.handles.insert(language.to_string(), handle);
-                self.handles.get_mut(language)
+            // Initialize the handle (non-blocking)
+            // The handle will become ready asynchronously. Servers sharing
+            // documents are all given positions in one encoding.
+            let encodings = if shares_documents {
+                shared_position_encodings()
+            } else {
+                client_position_encodings()
+            };
+            if let Err(e) = handle.initialize(
+                self.root_uri.clone(),
+                server.initialization_options.clone(),
+                encodings,
+            ) {
+                tracing::error!("Failed to send initialize command for {}: {}", label, e);
+                continue;
             }
-            Err(e) => {
-                tracing::error!("Failed to spawn LSP handle for {}: {}", language, e);
-                None
+
+            tracing::info!(
+                "LSP initialization started for {}, will be ready asynchronously",
+                label
+            );
+            spawned.push(RunningServer { label, handle });
+        }
+
+        let servers = self.handles.entry(language.to_string()).or_default();
+        servers.extend(spawned);
+        servers.sort_by_key(|server| {
+            labels
+                .iter()
+                .position(|label| *label == server.label)
+                .unwrap_or(usize::MAX)
+        });
+        if servers.is_empty() {
+            self.handles.remove(language);
+        }
+    }
+
+    /// Remove a running server, returning its handle
+    fn remove_server(&mut self, label: &str) -> Option<LspHandle> {
+        let language = label_language(label);
+        let servers = self.handles.get_mut(language)?;
+        let index = servers.iter().position(|server| server.label == label)?;
+        let server = servers.remove(index);
+        if servers.is_empty() {
+            self.handles.remove(language);
+        }
+        Some(server.handle)
+    }
+
+    /// Labels `name` stands for: a language's servers, or the one server a
+    /// label names
+    fn labels_of(&self, name: &str) -> Vec<String> {
+        if label_server(name).is_some() {
+            return vec![name.to_string()];
+        }
+        let mut labels: Vec<String> = self
+            .config
+            .get(name)
+            .map(|config| {
+                config
+                    .servers()
+                    .iter()
+                    .map(|server| config.label(name, server))
+                    .collect()
+            })
+            .unwrap_or_default();
+        for server in self.handles.get(name).into_iter().flatten() {
+            if !labels.contains(&server.label) {
+                labels.push(server.label.clone());
             }
         }
+        if labels.is_empty() {
+            labels.push(name.to_string());
+        }
+        labels
     }
 
     /// Handle a server crash by scheduling a restart with exponential backoff
     ///
-    /// Returns a message describing the action taken (for UI notification)
-    pub fn handle_server_crash(&mut self, language: &str) -> String {
+    /// `label` is the crashed server's label. Returns a message describing
+    /// the action taken (for UI notification)
+    pub fn handle_server_crash(&mut self, label: &str) -> String {
         // Remove the crashed handle
-        if let Some(handle) = self.handles.remove(language) {
+        if let Some(handle) = self.remove_server(label) {
             let _ = handle.shutdown(); // Best-effort cleanup
         }
 
         // Check if server was explicitly disabled by user (via stop command)
         // Don't auto-restart disabled servers
-        if self.disabled_languages.contains(language) {
+        if self.disabled_languages.contains(label)
+            || self.disabled_languages.contains(label_language(label))
+        {
             return format!(
                 "LSP server for {} stopped. Use 'Restart LSP Server' command to start it again.",
-                language
+                label
             );
         }
 
         // Check if we're in cooldown
-        if self.restart_cooldown.contains(language) {
+        if self.restart_cooldown.contains(label) {
             return format!(
                 "LSP server for {} crashed. Too many restarts - use 'Restart LSP Server' command to retry.",
-                language
+                label
             );
         }
 
         // Clean up old restart attempts outside the window
         let now = Instant::now();
         let window = Duration::from_secs(RESTART_WINDOW_SECS);
-        let attempts = self
-            .restart_attempts
-            .entry(language.to_string())
-            .or_default();
+        let attempts = self.restart_attempts.entry(label.to_string()).or_default();
         attempts.retain(|t| now.duration_since(*t) < window);
 
         // Check if we've exceeded max restarts
         if attempts.len() >= MAX_RESTARTS_IN_WINDOW {
-            self.restart_cooldown.insert(language.to_string());
+            self.restart_cooldown.insert(label.to_string());
             tracing::warn!(
                 "LSP server for {} has crashed {} times in {} minutes, entering cooldown",
-                language,
+                label,
                 MAX_RESTARTS_IN_WINDOW,
                 RESTART_WINDOW_SECS / 60
             );
             return format!(
                 "LSP server for {} has crashed too many times ({} in {} min). Use 'Restart LSP Server' command to manually restart.",
-                language,
+                label,
                 MAX_RESTARTS_IN_WINDOW,
                 RESTART_WINDOW_SECS / 60
             );
@@ -309,11 +492,11 @@ impl LspManager {
 
         // Schedule the restart
         self.pending_restarts
-            .insert(language.to_string(), restart_time);
+            .insert(label.to_string(), restart_time);
 
         tracing::info!(
             "LSP server for {} crashed (attempt {}/{}), will restart in {}ms",
-            language,
+            label,
             attempt_number + 1,
             MAX_RESTARTS_IN_WINDOW,
             delay_ms
@@ -321,7 +504,7 @@ impl LspManager {
 
         format!(
             "LSP server for {} crashed (attempt {}/{}), restarting in {}s...",
-            language,
+            label,
             attempt_number + 1,
             MAX_RESTARTS_IN_WINDOW,
             delay_ms / 1000
@@ -330,7 +513,7 @@ impl LspManager {
 
     /// Check and process any pending restarts that are due
     ///
-    /// Returns list of (language, success, message) for each restart attempted
+    /// Returns list of (server label, success, message) for each restart attempted
     pub fn process_pending_restarts(&mut self) -> Vec<(String, bool, String)> {
         let now = Instant::now();
         let mut results = Vec::new();
@@ -340,27 +523,27 @@ impl LspManager {
             .pending_restarts
             .iter()
             .filter(|(_, time)| **time <= now)
-            .map(|(lang, _)| lang.clone())
+            .map(|(label, _)| label.clone())
             .collect();
 
-        for language in due_restarts {
-            self.pending_restarts.remove(&language);
+        for label in due_restarts {
+            self.pending_restarts.remove(&label);
 
             // Record this restart attempt
             self.restart_attempts
-                .entry(language.clone())
+                .entry(label.clone())
                 .or_default()
                 .push(now);
 
             // Attempt to spawn the server
-            if self.get_or_spawn(&language).is_some() {
-                let message = format!("LSP server for {} restarted successfully", language);
+            if self.get_or_spawn_server(&label).is_some() {
+                let message = format!("LSP server for {} restarted successfully", label);
                 tracing::info!("{}", message);
-                results.push((language, true, message));
+                results.push((label, true, message));
             } else {
-                let message = format!("Failed to restart LSP server for {}", language);
+                let message = format!("Failed to restart LSP server for {}", label);
                 tracing::error!("{}", message);
-                results.push((language, false, message));
+                results.push((label, false, message));
             }
         }
 
@@ -368,62 +551,78 @@ impl LspManager {
     }
 
     /// Check if a language server is in restart cooldown
-    pub fn is_in_cooldown(&self, language: &str) -> bool {
-        self.restart_cooldown.contains(language)
+    pub fn is_in_cooldown(&self, label: &str) -> bool {
+        self.restart_cooldown.contains(label)
     }
 
     /// Check if a language server has a pending restart
-    pub fn has_pending_restart(&self, language: &str) -> bool {
-        self.pending_restarts.contains_key(language)
+    pub fn has_pending_restart(&self, label: &str) -> bool {
+        self.pending_restarts.contains_key(label)
     }
 
-    /// Clear cooldown for a language and allow manual restart
-    pub fn clear_cooldown(&mut self, language: &str) {
-        self.restart_cooldown.remove(language);
-        self.restart_attempts.remove(language);
-        self.pending_restarts.remove(language);
-        tracing::info!("Cleared restart cooldown for {}", language);
+    /// Clear cooldown for a language's servers, or the server a label
+    /// names, and allow manual restart
+    pub fn clear_cooldown(&mut self, name: &str) {
+        for label in self.labels_of(name) {
+            self.restart_cooldown.remove(&label);
+            self.restart_attempts.remove(&label);
+            self.pending_restarts.remove(&label);
+        }
+        tracing::info!("Cleared restart cooldown for {}", name);
     }
 
-    /// Manually restart/start a language server (bypasses cooldown and auto_start check)
+    /// Manually restart/start a language's servers, or the server a label
+    /// names (bypasses cooldown and auto_start check)
     ///
     /// This is used both to restart a crashed server and to manually start a server
     /// that has auto_start=false in its configuration.
     ///
     /// Returns (success, message) tuple
-    pub fn manual_restart(&mut self, language: &str) -> (bool, String) {
+    pub fn manual_restart(&mut self, name: &str) -> (bool, String) {
+        let language = label_language(name).to_string();
+
         // Clear any existing state
-        self.clear_cooldown(language);
+        self.clear_cooldown(name);
 
-        // Re-enable the language (remove from disabled set)
-        self.disabled_languages.remove(language);
+        // Re-enable the language or server (remove from disabled set)
+        self.disabled_languages.remove(&language);
+        for label in self.labels_of(name) {
+            self.disabled_languages.remove(&label);
+        }
 
         // Add to allowed languages so it stays active even if auto_start=false
-        self.allowed_languages.insert(language.to_string());
+        self.allowed_languages.insert(language.clone());
 
-        // Remove existing handle
-        if let Some(handle) = self.handles.remove(language) {
-            let _ = handle.shutdown();
+        // Remove existing handles
+        for label in self.labels_of(name) {
+            if let Some(handle) = self.remove_server(&label) {
+                let _ = handle.shutdown();
+            }
         }
 
-        // Spawn new server
-        if self.get_or_spawn(language).is_some() {
-            let message = format!("LSP server for {} started", language);
+        // Spawn new servers
+        let started = if label_server(name).is_some() {
+            self.get_or_spawn_server(name).is_some()
+        } else {
+            self.get_or_spawn(&language).is_some()
+        };
+        if started {
+            let message = format!("LSP server for {} started", name);
             tracing::info!("{}", message);
             (true, message)
         } else {
-            let message = format!("Failed to start LSP server for {}", language);
+            let message = format!("Failed to start LSP server for {}", name);
             tracing::error!("{}", message);
             (false, message)
         }
     }
 
-    /// Get the number of recent restart attempts for a language
-    pub fn restart_attempt_count(&self, language: &str) -> usize {
+    /// Get the number of recent restart attempts for a server
+    pub fn restart_attempt_count(&self, label: &str) -> usize {
         let now = Instant::now();
         let window = Duration::from_secs(RESTART_WINDOW_SECS);
         self.restart_attempts
-            .get(language)
+            .get(label)
             .map(|attempts| {
                 attempts
                     .iter()
@@ -433,55 +632,94 @@ impl LspManager {
             .unwrap_or(0)
     }
 
-    /// Get a list of currently running LSP server languages
+    /// Get a list of the languages with a running LSP server
     pub fn running_servers(&self) -> Vec<String> {
         self.handles.keys().cloned().collect()
     }
 
-    /// Check if an LSP server for a language is running and ready to serve requests
-    pub fn is_server_ready(&self, language: &str) -> bool {
+    /// Labels of the running LSP servers
+    pub fn running_server_labels(&self) -> Vec<String> {
         self.handles
-            .get(language)
-            .map(|handle| handle.state().can_send_requests())
-            .unwrap_or(false)
+            .values()
+            .flatten()
+            .map(|server| server.label.clone())
+            .collect()
+    }
+
+    /// Check if an LSP server for a language, or the server a label names,
+    /// is running and ready to serve requests
+    pub fn is_server_ready(&self, name: &str) -> bool {
+        if label_server(name).is_some() {
+            return self
+                .server(name)
+                .is_some_and(|handle| handle.state().can_send_requests());
+        }
+        self.handles.get(name).is_some_and(|servers| {
+            servers
+                .iter()
+                .any(|server| server.handle.state().can_send_requests())
+        })
     }
 
-    /// Shutdown a specific language server
+    /// Shutdown a language's servers, or the server a label names
     ///
     /// This marks the server as disabled, preventing auto-restart until the user
     /// explicitly restarts it using the restart command.
     ///
-    /// Returns true if the server was found and shutdown, false otherwise
-    pub fn shutdown_server(&mut self, language: &str) -> bool {
-        if let Some(handle) = self.handles.remove(language) {
-            tracing::info!(
-                "Shutting down LSP server for {} (disabled until manual restart)",
-                language
-            );
-            let _ = handle.shutdown();
-            // Mark as disabled to prevent auto-restart
-            self.disabled_languages.insert(language.to_string());
+    /// Returns true if a server was found and shutdown, false otherwise
+    pub fn shutdown_server(&mut self, name: &str) -> bool {
+        let mut found = false;
+        for label in self.labels_of(name) {
+            if let Some(handle) = self.remove_server(&label) {
+                tracing::info!(
+                    "Shutting down LSP server for {} (disabled until manual restart)",
+                    label
+                );
+                let _ = handle.shutdown();
+                found = true;
+            }
             // Cancel any pending restarts
-            self.pending_restarts.remove(language);
+            self.pending_restarts.remove(&label);
             // Remove from restart cooldown
-            self.restart_cooldown.remove(language);
+            self.restart_cooldown.remove(&label);
+        }
+        if !found {
+            tracing::warn!("No running LSP server found for {}", name);
+            return false;
+        }
+        // Mark as disabled to prevent auto-restart
+        self.disabled_languages.insert(name.to_string());
+        if label_server(name).is_none() {
             // Also remove from allowed languages so it will require confirmation again
             // if user tries to start it later
-            self.allowed_languages.remove(language);
-            true
-        } else {
-            tracing::warn!("No running LSP server found for {}", language);
-            false
+            self.allowed_languages.remove(name);
         }
+        true
     }
 
-    /// Shutdown all language servers
+    /// Shutdown all language servers and wait for them to exit
+    ///
+    /// The servers shut down in parallel, so this takes at most
+    /// [`GRACEFUL_SHUTDOWN_TIMEOUT`] however many are running; a server
+    /// that doesn't exit in time is killed.
     pub fn shutdown_all(&mut self) {
-        for (language, handle) in self.handles.iter() {
-            tracing::info!("Shutting down LSP server for {}", language);
-            let _ = handle.shutdown();
+        let mut stopping = Vec::new();
+        for server in self.handles.drain().flat_map(|(_, servers)| servers) {
+            let label = server.label;
+            tracing::info!("Shutting down LSP server for {}", label);
+            match server.handle.shutdown_notify() {
+                Ok(done) => stopping.push((label, done)),
+                Err(e) => tracing::warn!("Failed to shut down LSP server for {}: {}", label, e),
+            }
+        }
+
+        let deadline = Instant::now() + GRACEFUL_SHUTDOWN_TIMEOUT;
+        for (label, done) in stopping {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if let Err(std::sync::mpsc::RecvTimeoutError::Timeout) = done.recv_timeout(remaining) {
+                tracing::warn!("LSP server for {} did not shut down in time", label);
+            }
         }
-        self.handles.clear();
     }
 }
 
@@ -494,25 +732,22 @@ impl Drop for LspManager {
 /// Helper function to detect language from file path using the config's languages section.
 ///
 /// Checks in order:
-/// 1. File extension against `extensions`
-/// 2. Exact filename against `filenames`
+/// 1. Filename patterns against `filenames` (so `tsconfig.json` can be
+///    JSONC although its extension is `json`)
+/// 2. File extension against `extensions`
 pub fn detect_language(
     path: &std::path::Path,
     languages: &std::collections::HashMap<String, crate::config::LanguageConfig>,
 ) -> Option<String> {
-    // Try extension first
-    if let Some(extension) = path.extension().and_then(|e| e.to_str()) {
-        for (language_name, lang_config) in languages {
-            if lang_config.extensions.iter().any(|ext| ext == extension) {
-                return Some(language_name.clone());
-            }
+    for (language_name, lang_config) in languages {
+        if lang_config.matches_filename(path) {
+            return Some(language_name.clone());
         }
     }
 
-    // Try exact filename match
-    if let Some(filename) = path.file_name().and_then(|f| f.to_str()) {
+    if let Some(extension) = path.extension().and_then(|e| e.to_str()) {
         for (language_name, lang_config) in languages {
-            if lang_config.filenames.iter().any(|f| f == filename) {
+            if lang_config.extensions.iter().any(|ext| ext == extension) {
                 return Some(language_name.clone());
             }
         }
@@ -524,6 +759,7 @@ pub fn detect_language(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::types::LspServerConfig;
     use std::path::Path;
 
     #[test]
@@ -550,13 +786,62 @@ mod tests {
             process_limits: crate::services::process_limits::ProcessLimits::unlimited(),
             auto_start: false,
             initialization_options: None,
+            name: None,
+            primary: false,
         };
 
-        manager.set_language_config("rust".to_string(), config);
+        manager.set_language_config("rust".to_string(), config.into());
 
         assert_eq!(manager.config.len(), 1);
         assert!(manager.config.contains_key("rust"));
-        assert!(manager.config.get("rust").unwrap().enabled);
+        assert!(manager.config.get("rust").unwrap().servers()[0].enabled);
+        assert_eq!(manager.server_labels("rust"), ["rust"]);
+    }
+
+    fn server(command: &str, primary: bool) -> LspServerConfig {
+        LspServerConfig {
+            enabled: true,
+            command: command.to_string(),
+            args: vec![],
+            process_limits: crate::services::process_limits::ProcessLimits::unlimited(),
+            auto_start: true,
+            initialization_options: None,
+            name: None,
+            primary,
+        }
+    }
+
+    #[test]
+    fn test_server_labels_of_several_servers() {
+        let mut manager = LspManager::new(None);
+        let mut disabled = server("/opt/bin/mypy-lsp", false);
+        disabled.enabled = false;
+        manager.set_language_config(
+            "python".to_string(),
+            LspLanguageConfig::Multi(vec![
+                server("pyright-langserver", false),
+                server("/usr/bin/ruff", true),
+                disabled,
+            ]),
+        );
+
+        // The primary server comes first
+        assert_eq!(
+            manager.server_labels("python"),
+            ["python/ruff", "python/pyright-langserver"]
+        );
+        assert_eq!(label_language("python/ruff"), "python");
+        assert_eq!(label_server("python/ruff"), Some("ruff"));
+        assert_eq!(label_server("python"), None);
+
+        // Stopping one server leaves the other attached
+        manager.disabled_languages.insert("python/ruff".to_string());
+        assert_eq!(
+            manager.server_labels("python"),
+            ["python/pyright-langserver"]
+        );
+        manager.disabled_languages.insert("python".to_string());
+        assert!(manager.server_labels("python").is_empty());
     }
 
     #[test]
@@ -573,7 +858,10 @@ mod tests {
                 process_limits: crate::services::process_limits::ProcessLimits::unlimited(),
                 auto_start: false,
                 initialization_options: None,
-            },
+                name: None,
+                primary: false,
+            }
+            .into(),
         );
 
         // get_or_spawn should return None without runtime
@@ -612,7 +900,10 @@ mod tests {
                 process_limits: crate::services::process_limits::ProcessLimits::unlimited(),
                 auto_start: false,
                 initialization_options: None,
-            },
+                name: None,
+                primary: false,
+            }
+            .into(),
         );
 
         // get_or_spawn should return None for disabled language
@@ -647,6 +938,14 @@ mod tests {
                 formatter: None,
                 format_on_save: false,
                 on_save: vec![],
+                indent_rules: None,
+                on_type_formatting: false,
+                rulers: None,
+                auto_complete: true,
+                completion_trigger_characters: vec![],
+                save_actions: Vec::new(),
+                save_actions_on_failure: crate::config::SaveActionFailure::Continue,
+                save_actions_timeout_ms: 5000,
             },
         );
         languages.insert(
@@ -665,6 +964,14 @@ mod tests {
                 formatter: None,
                 format_on_save: false,
                 on_save: vec![],
+                indent_rules: None,
+                on_type_formatting: false,
+                rulers: None,
+                auto_complete: true,
+                completion_trigger_characters: vec![],
+                save_actions: Vec::new(),
+                save_actions_on_failure: crate::config::SaveActionFailure::Continue,
+                save_actions_timeout_ms: 5000,
             },
         );
         languages.insert(
@@ -683,6 +990,14 @@ mod tests {
                 formatter: None,
                 format_on_save: false,
                 on_save: vec![],
+                indent_rules: None,
+                on_type_formatting: false,
+                rulers: None,
+                auto_complete: true,
+                completion_trigger_characters: vec![],
+                save_actions: Vec::new(),
+                save_actions_on_failure: crate::config::SaveActionFailure::Continue,
+                save_actions_timeout_ms: 5000,
             },
         );
         languages
@@ -722,4 +1037,20 @@ mod tests {
         assert_eq!(detect_language(Path::new("README"), &languages), None);
         assert_eq!(detect_language(Path::new("Makefile"), &languages), None);
     }
+
+    #[test]
+    fn test_detect_jsonc_by_filename_pattern() {
+        let languages = crate::config::Config::default().languages;
+        let detect = |path: &str| detect_language(Path::new(path), &languages);
+
+        assert_eq!(detect("settings.jsonc"), Some("jsonc".to_string()));
+        assert_eq!(detect("tsconfig.json"), Some("jsonc".to_string()));
+        assert_eq!(detect("web/tsconfig.build.json"), Some("jsonc".to_string()));
+        assert_eq!(
+            detect("/home/me/project/.vscode/settings.json"),
+            Some("jsonc".to_string())
+        );
+        assert_eq!(detect("package.json"), Some("json".to_string()));
+        assert_eq!(detect("vscode/settings.json"), Some("json".to_string()));
+    }
 }
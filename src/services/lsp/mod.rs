@@ -30,7 +30,7 @@
 //! │                         LspManager                                      │
 //! │                                                                         │
 //! │  - One manager per editor instance                                     │
-//! │  - Manages multiple language servers (one per language)                │
+//! │  - Manages multiple language servers (one or more per language)        │
 //! │  - Handles server lifecycle (spawn, restart, shutdown)                 │
 //! │  - Restart throttling with exponential backoff                         │
 //! │  - Manual start/stop support via command palette                       │
@@ -67,7 +67,10 @@
 //!
 //! - **`manager`**: [`LspManager`] - Manages multiple language servers, handles
 //!   spawning/restarting, routes requests by language. Includes restart throttling
-//!   with exponential backoff to prevent server crash loops.
+//!   with exponential backoff to prevent server crash loops. A language may have
+//!   several servers: documents go to all of them, requests that can be merged
+//!   are asked of all (the editor's `lsp_fanout` merges the answers), and the
+//!   rest only of the language's primary server.
 //!
 //! - **`async_handler`**: [`LspHandle`] and `LspTask` - The async LSP client
 //!   implementation. `LspHandle` is a sync handle that sends commands via tokio
@@ -77,6 +80,9 @@
 //! - **`diagnostics`**: Converts LSP diagnostics to editor overlays (colored
 //!   underlines for errors, warnings, etc.).
 //!
+//! - **`uri`**: Converts between file paths and `file://` URIs, for both
+//!   directions of the protocol.
+//!
 //! # Message Flow
 //!
 //! ## Outgoing Requests (Editor → Server)
@@ -115,8 +121,11 @@
 //!   (e.g., pull diagnostics only if `diagnosticProvider` is advertised)
 
 pub mod async_handler;
+pub mod diagnostic_store;
 pub mod diagnostics;
+pub mod document_sync;
 pub mod manager;
+pub mod uri;
 
 // Re-export for public API (used by tests)
-pub use crate::types::LspServerConfig;
+pub use crate::types::{LspLanguageConfig, LspServerConfig};
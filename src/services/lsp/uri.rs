@@ -0,0 +1,365 @@
+//! Conversion between file paths and `file://` URIs
+//!
+//! Language servers and plugins talk in URIs, buffers are keyed by path.
+//! Everything crossing between the two goes through here so that a path with
+//! spaces or non-ASCII characters has exactly one URI, and a URI with
+//! percent-escapes opens the file it names rather than one with `%20` in its
+//! name.
+//!
+//! Decoding accepts the forms servers send: `file:///home/me/a%20b.rs`,
+//! Windows drives as `file:///C:/src` or `file:///c%3A/src`, and UNC shares as
+//! `file://server/share/x`. Other schemes (`untitled:`, `https:`, ...) don't
+//! name a local file and are rejected with [`UriError::UnsupportedScheme`].
+//! Encoding escapes everything outside the characters RFC 3986 allows in a
+//! path, so [`lsp_types::Uri`] always accepts the result.
+
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// Why a URI can't be turned into a path
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UriError {
+    /// A scheme other than `file`, such as `untitled` or `https`
+    UnsupportedScheme(String),
+    /// Bad percent-escapes, or escapes that don't decode to UTF-8
+    Malformed(String),
+}
+
+impl fmt::Display for UriError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnsupportedScheme(scheme) => {
+                write!(f, "'{}:' URIs don't name a local file", scheme)
+            }
+            Self::Malformed(uri) => write!(f, "malformed file URI: {}", uri),
+        }
+    }
+}
+
+impl std::error::Error for UriError {}
+
+/// Scheme of `input` if it is written as a URI
+///
+/// Schemes must be at least two characters long, so a Windows path like
+/// `C:\src` is not mistaken for one.
+pub fn uri_scheme(input: &str) -> Option<&str> {
+    let (scheme, _) = input.split_once(':')?;
+    let mut chars = scheme.chars();
+    let valid = scheme.len() >= 2
+        && chars.next().is_some_and(|c| c.is_ascii_alphabetic())
+        && chars.all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'));
+    valid.then_some(scheme)
+}
+
+/// Path named by a `file://` URI
+pub fn uri_to_path(uri: &str) -> Result<PathBuf, UriError> {
+    let path = decode_file_uri(uri)?;
+    #[cfg(windows)]
+    let path = path.replace('/', "\\");
+    Ok(PathBuf::from(path))
+}
+
+/// Path named by `input`, which is either a path or a `file://` URI
+///
+/// Used where plugins pass locations that may have come from a language
+/// server.
+pub fn path_or_uri_to_path(input: &str) -> Result<PathBuf, UriError> {
+    if uri_scheme(input).is_some() {
+        uri_to_path(input)
+    } else {
+        Ok(PathBuf::from(input))
+    }
+}
+
+/// `file://` URI of an absolute path; None for relative paths
+pub fn path_to_uri(path: &Path) -> Option<lsp_types::Uri> {
+    let path = path.to_str()?;
+    #[cfg(windows)]
+    let path = {
+        // Verbatim paths: `\\?\C:\src` and `\\?\UNC\server\share`
+        let path = path.replace('\\', "/");
+        match path.strip_prefix("//?/") {
+            Some(rest) => match rest.strip_prefix("UNC/") {
+                Some(unc) => format!("//{}", unc),
+                None => rest.to_string(),
+            },
+            None => path,
+        }
+    };
+    #[cfg(windows)]
+    let path = path.as_str();
+    encode_file_uri(path)?.parse().ok()
+}
+
+/// Canonical spelling of a URI, so one file always gets the same key
+///
+/// File URIs are decoded and encoded again; other URIs are kept as they are.
+pub fn normalize_uri(uri: &str) -> String {
+    decode_file_uri(uri)
+        .ok()
+        .and_then(|path| encode_file_uri(&path))
+        .unwrap_or_else(|| uri.to_string())
+}
+
+/// Decode a file URI to a `/`-separated path: `/home/me/a b.rs`,
+/// `C:/src/main.rs`, or `//server/share/x` for a UNC share
+fn decode_file_uri(uri: &str) -> Result<String, UriError> {
+    let malformed = || UriError::Malformed(uri.to_string());
+    let scheme = uri_scheme(uri).ok_or_else(malformed)?;
+    if !scheme.eq_ignore_ascii_case("file") {
+        return Err(UriError::UnsupportedScheme(scheme.to_string()));
+    }
+    let rest = &uri[scheme.len() + 1..];
+    // The query and fragment aren't part of the path
+    let rest = rest.split(['?', '#']).next().unwrap_or_default();
+    let (host, path) = match rest.strip_prefix("//") {
+        Some(authority_and_path) => match authority_and_path.find('/') {
+            Some(slash) => authority_and_path.split_at(slash),
+            None => (authority_and_path, ""),
+        },
+        None => ("", rest),
+    };
+    let host = percent_decode(host).ok_or_else(malformed)?;
+    let path = percent_decode(path).ok_or_else(malformed)?;
+    let path = if path.is_empty() {
+        "/".to_string()
+    } else {
+        path
+    };
+
+    if !host.is_empty() && !host.eq_ignore_ascii_case("localhost") {
+        return Ok(format!("//{}{}", host, path));
+    }
+    // `/C:/src` (or the old `/C|/src`) is a Windows drive
+    let bytes = path.as_bytes();
+    if bytes.len() >= 3
+        && bytes[0] == b'/'
+        && bytes[1].is_ascii_alphabetic()
+        && matches!(bytes[2], b':' | b'|')
+        && (bytes.len() == 3 || bytes[3] == b'/')
+    {
+        let drive = (bytes[1] as char).to_ascii_uppercase();
+        let rest = if bytes.len() == 3 { "/" } else { &path[3..] };
+        return Ok(format!("{}:{}", drive, rest));
+    }
+    Ok(path)
+}
+
+/// Encode a `/`-separated absolute path (as produced by [`decode_file_uri`])
+fn encode_file_uri(path: &str) -> Option<String> {
+    if let Some(unc) = path.strip_prefix("//") {
+        let (host, rest) = unc.split_at(unc.find('/').unwrap_or(unc.len()));
+        if host.is_empty() {
+            return None;
+        }
+        return Some(format!(
+            "file://{}{}",
+            percent_encode(host),
+            percent_encode(rest)
+        ));
+    }
+    let bytes = path.as_bytes();
+    if bytes.len() >= 2 && bytes[0].is_ascii_alphabetic() && bytes[1] == b':' {
+        return Some(format!(
+            "file:///{}:{}",
+            &path[..1],
+            percent_encode(&path[2..])
+        ));
+    }
+    path.starts_with('/')
+        .then(|| format!("file://{}", percent_encode(path)))
+}
+
+/// Escape everything RFC 3986 doesn't allow in a path
+fn percent_encode(text: &str) -> String {
+    let mut encoded = String::with_capacity(text.len());
+    for &byte in text.as_bytes() {
+        let allowed = byte.is_ascii_alphanumeric()
+            || matches!(
+                byte,
+                b'-' | b'.'
+                    | b'_'
+                    | b'~'
+                    | b'!'
+                    | b'$'
+                    | b'&'
+                    | b'\''
+                    | b'('
+                    | b')'
+                    | b'*'
+                    | b'+'
+                    | b','
+                    | b';'
+                    | b'='
+                    | b':'
+                    | b'@'
+                    | b'/'
+            );
+        if allowed {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    encoded
+}
+
+/// Decode `%XX` escapes; None if one is cut short or the result isn't UTF-8
+fn percent_decode(text: &str) -> Option<String> {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let hex = text
+                .get(i + 1..i + 3)
+                .filter(|hex| hex.bytes().all(|b| b.is_ascii_hexdigit()))?;
+            decoded.push(u8::from_str_radix(hex, 16).ok()?);
+            i += 3;
+        } else {
+            decoded.push(bytes[i]);
+            i += 1;
+        }
+    }
+    String::from_utf8(decoded).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_spaces_and_non_ascii() {
+        assert_eq!(
+            decode_file_uri("file:///home/me/My%20Project/src/main.rs").unwrap(),
+            "/home/me/My Project/src/main.rs"
+        );
+        assert_eq!(
+            decode_file_uri("file:///home/me/%C3%BCber/%E6%97%A5%E6%9C%AC.rs").unwrap(),
+            "/home/me/über/日本.rs"
+        );
+        // Unescaped characters are taken as they are
+        assert_eq!(
+            decode_file_uri("file:///home/me/über.rs").unwrap(),
+            "/home/me/über.rs"
+        );
+        assert_eq!(
+            decode_file_uri("file://localhost/etc/hosts").unwrap(),
+            "/etc/hosts"
+        );
+    }
+
+    #[test]
+    fn test_decode_windows_drives() {
+        assert_eq!(
+            decode_file_uri("file:///C:/Users/me/a%20b.rs").unwrap(),
+            "C:/Users/me/a b.rs"
+        );
+        assert_eq!(
+            decode_file_uri("file:///c%3A/Users/me/a.rs").unwrap(),
+            "C:/Users/me/a.rs"
+        );
+        assert_eq!(decode_file_uri("file:///d:").unwrap(), "D:/");
+        // A path segment that only starts like a drive isn't one
+        assert_eq!(decode_file_uri("file:///C:x/y").unwrap(), "/C:x/y");
+    }
+
+    #[test]
+    fn test_decode_unc() {
+        assert_eq!(
+            decode_file_uri("file://server/share/My%20Docs/a.txt").unwrap(),
+            "//server/share/My Docs/a.txt"
+        );
+        assert_eq!(
+            encode_file_uri("//server/share/My Docs/a.txt").unwrap(),
+            "file://server/share/My%20Docs/a.txt"
+        );
+    }
+
+    #[test]
+    fn test_rejects_other_schemes_and_bad_escapes() {
+        assert_eq!(
+            decode_file_uri("https://example.com/src/main.rs"),
+            Err(UriError::UnsupportedScheme("https".to_string()))
+        );
+        assert_eq!(
+            decode_file_uri("untitled:Untitled-1"),
+            Err(UriError::UnsupportedScheme("untitled".to_string()))
+        );
+        assert!(matches!(
+            decode_file_uri("file:///tmp/a%2"),
+            Err(UriError::Malformed(_))
+        ));
+        assert!(matches!(
+            decode_file_uri("file:///tmp/%+1"),
+            Err(UriError::Malformed(_))
+        ));
+        assert!(matches!(
+            decode_file_uri("file:///tmp/%FF"),
+            Err(UriError::Malformed(_))
+        ));
+    }
+
+    #[test]
+    fn test_encode_round_trips() {
+        for path in [
+            "/home/me/My Project/src/main.rs",
+            "/home/me/über/日本.rs",
+            "/tmp/100% #1 [draft]?.md",
+            "C:/Users/me/a b.rs",
+            "//server/share/x y",
+        ] {
+            let uri = encode_file_uri(path).unwrap();
+            assert!(uri.parse::<lsp_types::Uri>().is_ok(), "{}", uri);
+            assert_eq!(decode_file_uri(&uri).unwrap(), path);
+        }
+        assert_eq!(
+            encode_file_uri("/home/me/My Project/ü.rs").unwrap(),
+            "file:///home/me/My%20Project/%C3%BC.rs"
+        );
+        assert_eq!(encode_file_uri("relative/path"), None);
+    }
+
+    #[test]
+    fn test_normalize_uri() {
+        assert_eq!(
+            normalize_uri("file:///c%3A/src/a%2Eb.rs"),
+            "file:///C:/src/a.b.rs"
+        );
+        assert_eq!(
+            normalize_uri("file:///home/me/My Project/x.rs"),
+            normalize_uri("file:///home/me/My%20Project/x.rs")
+        );
+        assert_eq!(normalize_uri("untitled:Untitled-1"), "untitled:Untitled-1");
+    }
+
+    #[test]
+    fn test_path_or_uri() {
+        assert_eq!(
+            path_or_uri_to_path("src/main.rs").unwrap(),
+            PathBuf::from("src/main.rs")
+        );
+        assert_eq!(
+            path_or_uri_to_path("C:\\src\\main.rs").unwrap(),
+            PathBuf::from("C:\\src\\main.rs")
+        );
+        assert!(path_or_uri_to_path("https://example.com/x").is_err());
+        #[cfg(unix)]
+        assert_eq!(
+            path_or_uri_to_path("file:///tmp/a%20b").unwrap(),
+            PathBuf::from("/tmp/a b")
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_path_to_uri() {
+        let uri = path_to_uri(Path::new("/home/me/My Project/main.rs")).unwrap();
+        assert_eq!(uri.as_str(), "file:///home/me/My%20Project/main.rs");
+        assert_eq!(
+            uri_to_path(uri.as_str()).unwrap(),
+            PathBuf::from("/home/me/My Project/main.rs")
+        );
+        assert!(path_to_uri(Path::new("relative.rs")).is_none());
+    }
+}
@@ -4,19 +4,42 @@
 //! I/O, and async operations.
 
 pub mod async_bridge;
+pub mod async_queue;
+pub mod cache_budget;
 pub mod clipboard;
+pub mod command_trust;
+pub mod dir_compare;
+pub mod dir_loader;
+pub mod file_access;
+pub mod file_loader;
+pub mod file_provider;
+pub mod file_saver;
+pub mod formatter;
 pub mod fs;
+pub mod git_blame;
+pub mod gitattributes;
 #[cfg(target_os = "linux")]
 pub mod gpm;
 pub mod log_dirs;
 pub mod lsp;
+pub mod open_target;
+pub mod path_watch;
 pub mod plugins;
 pub mod process_limits;
+pub mod recent_projects;
 pub mod recovery;
 pub mod release_checker;
+pub mod remote;
+pub mod rotating_log;
+pub mod settings_archive;
 pub mod signal_handler;
+pub mod startup_profile;
 pub mod styled_html;
 pub mod terminal;
 pub mod time_source;
+pub mod todo_scanner;
 pub mod tracing_setup;
+pub mod usage_stats;
 pub mod warning_log;
+pub mod word_index;
+pub mod worker_pool;
@@ -0,0 +1,145 @@
+//! What a path given to open refers to
+//!
+//! Everything that opens files by path (command-line arguments, plugins, LSP
+//! locations, restored sessions) classifies the path first, so directories
+//! can go to the file explorer, missing files become new buffers, and FIFOs,
+//! sockets and devices are refused instead of blocking the editor in
+//! `open(2)`. Classifying only stats the path; it never opens it.
+
+use std::fs::Metadata;
+use std::io;
+use std::path::Path;
+
+/// What a path refers to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OpenTarget {
+    /// A regular file, possibly through a symlink
+    File,
+    /// A directory, possibly through a symlink
+    Directory,
+    /// Nothing, including a symlink whose target is missing
+    Missing,
+    /// Something that isn't a file or directory
+    Special(SpecialFile),
+}
+
+/// Kinds of paths that can't be opened as a buffer
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpecialFile {
+    /// A named pipe, which blocks on open until a writer shows up
+    Fifo,
+    Socket,
+    BlockDevice,
+    CharDevice,
+    /// Any other kind the platform reports
+    Other,
+}
+
+impl SpecialFile {
+    /// Name of the kind for messages
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Fifo => "fifo",
+            Self::Socket => "socket",
+            Self::BlockDevice => "block device",
+            Self::CharDevice => "character device",
+            Self::Other => "special file",
+        }
+    }
+}
+
+/// What `path` refers to, following symlinks
+///
+/// Errors other than the path not existing, such as lacking permission to
+/// look into its directory, are returned as they are.
+pub fn classify(path: &Path) -> io::Result<OpenTarget> {
+    match std::fs::metadata(path) {
+        Ok(metadata) => Ok(classify_metadata(&metadata)),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(OpenTarget::Missing),
+        Err(e) => Err(e),
+    }
+}
+
+fn classify_metadata(metadata: &Metadata) -> OpenTarget {
+    let file_type = metadata.file_type();
+    if file_type.is_file() {
+        return OpenTarget::File;
+    }
+    if file_type.is_dir() {
+        return OpenTarget::Directory;
+    }
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::FileTypeExt;
+        if file_type.is_fifo() {
+            return OpenTarget::Special(SpecialFile::Fifo);
+        }
+        if file_type.is_socket() {
+            return OpenTarget::Special(SpecialFile::Socket);
+        }
+        if file_type.is_block_device() {
+            return OpenTarget::Special(SpecialFile::BlockDevice);
+        }
+        if file_type.is_char_device() {
+            return OpenTarget::Special(SpecialFile::CharDevice);
+        }
+    }
+    OpenTarget::Special(SpecialFile::Other)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify_files_directories_and_missing_paths() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("notes.txt");
+        std::fs::write(&file, "text").unwrap();
+
+        assert_eq!(classify(&file).unwrap(), OpenTarget::File);
+        assert_eq!(classify(dir.path()).unwrap(), OpenTarget::Directory);
+        assert_eq!(
+            classify(&dir.path().join("new.txt")).unwrap(),
+            OpenTarget::Missing
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_classify_follows_symlinks() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("notes.txt");
+        std::fs::write(&file, "text").unwrap();
+        let file_link = dir.path().join("file_link");
+        let dir_link = dir.path().join("dir_link");
+        let dangling = dir.path().join("dangling");
+        std::os::unix::fs::symlink(&file, &file_link).unwrap();
+        std::os::unix::fs::symlink(dir.path(), &dir_link).unwrap();
+        std::os::unix::fs::symlink(dir.path().join("gone.txt"), &dangling).unwrap();
+
+        assert_eq!(classify(&file_link).unwrap(), OpenTarget::File);
+        assert_eq!(classify(&dir_link).unwrap(), OpenTarget::Directory);
+        assert_eq!(classify(&dangling).unwrap(), OpenTarget::Missing);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_classify_special_files_without_opening_them() {
+        let dir = tempfile::tempdir().unwrap();
+        let fifo = dir.path().join("pipe");
+        let c_path = std::ffi::CString::new(fifo.to_str().unwrap()).unwrap();
+        // SAFETY: c_path is a valid NUL-terminated string for the call's duration
+        assert_eq!(unsafe { libc::mkfifo(c_path.as_ptr(), 0o600) }, 0);
+
+        // Opening the FIFO would block here with no writer
+        assert_eq!(
+            classify(&fifo).unwrap(),
+            OpenTarget::Special(SpecialFile::Fifo)
+        );
+        assert_eq!(
+            classify(Path::new("/dev/null")).unwrap(),
+            OpenTarget::Special(SpecialFile::CharDevice)
+        );
+    }
+}
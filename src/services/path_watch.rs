@@ -0,0 +1,337 @@
+//! Watches of files and globs on disk, for plugins and the theme file
+//!
+//! A [`PathWatch`] covers either a single path or a glob. A glob is matched
+//! against the entries under its root, the leading part of the pattern
+//! without glob characters, so one scan of the root serves every file the
+//! glob matches instead of one watch per file.
+//!
+//! Like auto-revert, changes are found by comparing modification times (and
+//! sizes) between polls. A changed path is reported once it stays unchanged
+//! for a whole poll, so several quick writes to a file produce one event.
+
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use std::collections::HashMap;
+use std::path::{Component, Path, PathBuf};
+use std::time::SystemTime;
+
+/// Most entries scanned under the root of a glob on each poll
+pub const MAX_SCANNED_ENTRIES: usize = 10_000;
+
+/// What happened to a watched path
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PathChangeKind {
+    Created,
+    Modified,
+    Removed,
+}
+
+impl PathChangeKind {
+    /// Name of the kind in hook payloads
+    pub fn as_str(self) -> &'static str {
+        match self {
+            PathChangeKind::Created => "created",
+            PathChangeKind::Modified => "modified",
+            PathChangeKind::Removed => "removed",
+        }
+    }
+
+    /// Combine a change still waiting to be reported with a newer one
+    ///
+    /// Returns None if the two cancel out (a file created and removed again).
+    pub(crate) fn then(self, newer: PathChangeKind) -> Option<PathChangeKind> {
+        use PathChangeKind::*;
+        match (self, newer) {
+            (Created, Removed) => None,
+            (Created, _) => Some(Created),
+            (Removed, Created) => Some(Modified),
+            (_, newer) => Some(newer),
+        }
+    }
+}
+
+/// A change to report to the watch's owner
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PathChange {
+    pub path: PathBuf,
+    pub kind: PathChangeKind,
+}
+
+/// Modification time and size of a file when it was last seen
+type Signature = (Option<SystemTime>, u64);
+
+/// A watched path or glob
+pub struct PathWatch {
+    /// The watched path, or the directory scanned for a glob
+    root: PathBuf,
+    /// Glob matcher, rooted at `root`; None when watching `root` itself
+    glob: Option<Gitignore>,
+    /// How deep below `root` the glob can match (None: any depth)
+    max_depth: Option<usize>,
+    /// Files as of the last poll
+    snapshot: HashMap<PathBuf, Signature>,
+    /// Changes waiting for their path to settle
+    pending: HashMap<PathBuf, PathChangeKind>,
+}
+
+impl PathWatch {
+    /// Watch a path or glob, relative paths being taken from `working_dir`
+    ///
+    /// Files that exist now are not reported; only later changes are.
+    pub fn new(pattern: &str, working_dir: &Path) -> Result<Self, String> {
+        let full = working_dir.join(pattern);
+
+        // Split off the leading components without glob characters
+        let mut root = PathBuf::new();
+        let mut rest: Vec<String> = Vec::new();
+        for component in full.components() {
+            let text = component.as_os_str().to_string_lossy();
+            if rest.is_empty() && !is_glob(&text) {
+                root.push(component);
+            } else if !matches!(component, Component::CurDir) {
+                rest.push(text.into_owned());
+            }
+        }
+
+        let (glob, max_depth) = if rest.is_empty() {
+            (None, None)
+        } else {
+            let rest = rest.join("/");
+            let mut builder = GitignoreBuilder::new(&root);
+            builder
+                .add_line(None, &format!("/{}", rest))
+                .map_err(|e| format!("invalid glob '{}': {}", pattern, e))?;
+            let matcher = builder
+                .build()
+                .map_err(|e| format!("invalid glob '{}': {}", pattern, e))?;
+            let max_depth = (!rest.contains("**")).then(|| rest.split('/').count());
+            (Some(matcher), max_depth)
+        };
+
+        let mut watch = Self {
+            root,
+            glob,
+            max_depth,
+            snapshot: HashMap::new(),
+            pending: HashMap::new(),
+        };
+        watch.snapshot = watch.scan();
+        Ok(watch)
+    }
+
+    /// Look for changes since the last poll
+    ///
+    /// Returns the changes of paths that did not change again since the
+    /// previous poll, sorted by path.
+    pub fn poll(&mut self) -> Vec<PathChange> {
+        let current = self.scan();
+        let mut changed: Vec<(PathBuf, PathChangeKind)> = Vec::new();
+        for (path, signature) in &current {
+            match self.snapshot.get(path) {
+                None => changed.push((path.clone(), PathChangeKind::Created)),
+                Some(old) if old != signature => {
+                    changed.push((path.clone(), PathChangeKind::Modified))
+                }
+                Some(_) => {}
+            }
+        }
+        for path in self.snapshot.keys() {
+            if !current.contains_key(path) {
+                changed.push((path.clone(), PathChangeKind::Removed));
+            }
+        }
+        self.snapshot = current;
+
+        // Paths that stayed quiet since their last change are ready
+        let mut settled: Vec<PathChange> = Vec::new();
+        self.pending.retain(|path, kind| {
+            if changed.iter().any(|(p, _)| p == path) {
+                return true;
+            }
+            settled.push(PathChange {
+                path: path.clone(),
+                kind: *kind,
+            });
+            false
+        });
+        settled.sort_by(|a, b| a.path.cmp(&b.path));
+
+        for (path, kind) in changed {
+            let merged = match self.pending.get(&path) {
+                Some(pending) => pending.then(kind),
+                None => Some(kind),
+            };
+            match merged {
+                Some(kind) => {
+                    self.pending.insert(path, kind);
+                }
+                None => {
+                    self.pending.remove(&path);
+                }
+            }
+        }
+
+        settled
+    }
+
+    /// Current signatures of the watched files
+    fn scan(&self) -> HashMap<PathBuf, Signature> {
+        let mut files = HashMap::new();
+        match &self.glob {
+            None => {
+                if let Ok(metadata) = std::fs::metadata(&self.root) {
+                    files.insert(self.root.clone(), signature(&metadata));
+                }
+            }
+            Some(glob) => {
+                let mut scanned = 0;
+                self.scan_dir(glob, &self.root, 1, &mut scanned, &mut files);
+            }
+        }
+        files
+    }
+
+    fn scan_dir(
+        &self,
+        glob: &Gitignore,
+        dir: &Path,
+        depth: usize,
+        scanned: &mut usize,
+        files: &mut HashMap<PathBuf, Signature>,
+    ) {
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            return;
+        };
+        for entry in entries.flatten() {
+            *scanned += 1;
+            if *scanned > MAX_SCANNED_ENTRIES {
+                return;
+            }
+            let Ok(file_type) = entry.file_type() else {
+                continue;
+            };
+            let path = entry.path();
+            // Symlinked directories are not followed, to avoid cycles
+            if file_type.is_dir() {
+                if self.max_depth.is_none_or(|max| depth < max) {
+                    self.scan_dir(glob, &path, depth + 1, scanned, files);
+                }
+            } else if glob.matched(&path, false).is_ignore() {
+                if let Ok(metadata) = entry.metadata() {
+                    files.insert(path, signature(&metadata));
+                }
+            }
+        }
+    }
+}
+
+fn signature(metadata: &std::fs::Metadata) -> Signature {
+    (metadata.modified().ok(), metadata.len())
+}
+
+fn is_glob(component: &str) -> bool {
+    component.contains(['*', '?', '[', '{'])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn change(path: PathBuf, kind: PathChangeKind) -> PathChange {
+        PathChange { path, kind }
+    }
+
+    #[test]
+    fn test_single_path_reports_each_kind_once_settled() {
+        let dir = TempDir::new().unwrap();
+        let file = dir.path().join("tags");
+        let mut watch = PathWatch::new("tags", dir.path()).unwrap();
+
+        std::fs::write(&file, "a").unwrap();
+        assert!(watch.poll().is_empty());
+        assert_eq!(
+            watch.poll(),
+            vec![change(file.clone(), PathChangeKind::Created)]
+        );
+
+        std::fs::write(&file, "ab").unwrap();
+        assert!(watch.poll().is_empty());
+        assert_eq!(
+            watch.poll(),
+            vec![change(file.clone(), PathChangeKind::Modified)]
+        );
+
+        std::fs::remove_file(&file).unwrap();
+        assert!(watch.poll().is_empty());
+        assert_eq!(watch.poll(), vec![change(file, PathChangeKind::Removed)]);
+        assert!(watch.poll().is_empty());
+    }
+
+    #[test]
+    fn test_rapid_writes_produce_one_event() {
+        let dir = TempDir::new().unwrap();
+        let file = dir.path().join("build.log");
+        std::fs::write(&file, "").unwrap();
+        let mut watch = PathWatch::new("build.log", dir.path()).unwrap();
+
+        for content in ["1", "12", "123"] {
+            std::fs::write(&file, content).unwrap();
+            assert!(watch.poll().is_empty());
+        }
+        assert_eq!(watch.poll(), vec![change(file, PathChangeKind::Modified)]);
+        assert!(watch.poll().is_empty());
+    }
+
+    #[test]
+    fn test_glob_matches_files_under_its_root() {
+        let dir = TempDir::new().unwrap();
+        std::fs::create_dir_all(dir.path().join("data/nested")).unwrap();
+        let mut watch = PathWatch::new("data/*.json", dir.path()).unwrap();
+
+        let matching = dir.path().join("data/a.json");
+        std::fs::write(&matching, "{}").unwrap();
+        std::fs::write(dir.path().join("data/a.txt"), "").unwrap();
+        std::fs::write(dir.path().join("data/nested/b.json"), "{}").unwrap();
+        std::fs::write(dir.path().join("c.json"), "{}").unwrap();
+
+        watch.poll();
+        assert_eq!(
+            watch.poll(),
+            vec![change(matching, PathChangeKind::Created)]
+        );
+    }
+
+    #[test]
+    fn test_recursive_glob_matches_at_any_depth() {
+        let dir = TempDir::new().unwrap();
+        std::fs::create_dir_all(dir.path().join("src/deep/er")).unwrap();
+        let mut watch = PathWatch::new("src/**/*.rs", dir.path()).unwrap();
+
+        let top = dir.path().join("src/lib.rs");
+        let deep = dir.path().join("src/deep/er/mod.rs");
+        std::fs::write(&top, "").unwrap();
+        std::fs::write(&deep, "").unwrap();
+
+        watch.poll();
+        assert_eq!(
+            watch.poll(),
+            vec![
+                change(deep, PathChangeKind::Created),
+                change(top, PathChangeKind::Created),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_created_then_removed_before_settling_is_not_reported() {
+        let dir = TempDir::new().unwrap();
+        let file = dir.path().join("tmp.lock");
+        let mut watch = PathWatch::new("*.lock", dir.path()).unwrap();
+
+        std::fs::write(&file, "").unwrap();
+        watch.poll();
+        std::fs::remove_file(&file).unwrap();
+        assert!(watch.poll().is_empty());
+        assert!(watch.poll().is_empty());
+    }
+}
@@ -3,13 +3,25 @@
 //! This module provides a safe, controlled API for plugins (Lua, WASM, etc.)
 //! to interact with the editor without direct access to internal state.
 
+use crate::input::command_args::CommandArg;
 use crate::input::command_registry::CommandRegistry;
 use crate::input::commands::Command;
 use crate::model::event::{BufferId, SplitId};
+use crate::model::pending_edits::PendingEdit;
+use crate::primitives::display_text::{
+    sanitize_buffer_text, sanitize_line, sanitize_multiline, MAX_INLINE_CHARS, MAX_LABEL_CHARS,
+    MAX_LINE_CHARS, MAX_MESSAGE_CHARS, MAX_STATUS_CHARS, MAX_SYMBOL_CHARS,
+};
+use crate::services::file_access::ReadOnlyReason;
+use crate::services::lsp::diagnostic_store::DiagnosticsSummary;
 use crate::services::plugins::hooks::{HookCallback, HookRegistry};
+use crate::view::margin::GutterAlignment;
+use crate::view::notifications::NotificationSeverity;
 use crate::view::overlay::{OverlayHandle, OverlayNamespace};
+use crate::view::theme::DecorationColor;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use std::borrow::Cow;
 use std::collections::HashMap;
 use std::ops::Range;
 use std::path::PathBuf;
@@ -39,6 +51,36 @@ pub enum PluginResponse {
         request_id: u64,
         text: Result<String, String>,
     },
+    /// Response to ReviewPendingEdits with the user's final selection (None if cancelled)
+    PendingEditsReviewed {
+        request_id: u64,
+        edits: Option<Vec<PendingEdit>>,
+    },
+    /// Response to ExecuteActionWithResult/ExecuteActionsWithResult, one result per action run
+    ActionsExecuted {
+        request_id: u64,
+        results: Vec<ActionResult>,
+    },
+    /// Response to GetKeymap with the effective bindings, or why the context is invalid
+    Keymap {
+        request_id: u64,
+        entries: Result<Vec<crate::input::keybindings::KeymapEntry>, String>,
+    },
+    /// Response to ByteToLineCol with the (line, column) of the byte
+    LineCol {
+        request_id: u64,
+        position: Result<(usize, usize), String>,
+    },
+    /// Response to LineColToByte with the byte offset of the position
+    ByteOffset {
+        request_id: u64,
+        offset: Result<usize, String>,
+    },
+    /// Response to CreateBuffer with the new buffer's ID
+    BufferCreated {
+        request_id: u64,
+        buffer_id: Result<BufferId, String>,
+    },
 }
 
 /// Information about a cursor in the editor
@@ -59,6 +101,32 @@ pub struct ActionSpec {
     pub count: u32,
 }
 
+/// Options a plugin action (the global function behind a registered
+/// command) is called with
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ActionOptions {
+    /// Report what the command would do instead of doing it; only set for
+    /// commands registered with dry-run support
+    pub dry_run: bool,
+    /// Values of the parameters the command was registered with, by name;
+    /// absent for commands without parameters
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub args: Option<serde_json::Map<String, Value>>,
+}
+
+/// Outcome of an action executed on behalf of a plugin
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ActionResult {
+    /// Whether the action exists and ran without error
+    pub ok: bool,
+    /// Why the action failed (unknown action name or execution error)
+    pub error: Option<String>,
+    /// Whether the active buffer or its primary cursor position changed
+    pub cursor_moved: bool,
+    /// Whether the content of the buffer that was active changed
+    pub buffer_changed: bool,
+}
+
 /// Information about a buffer
 #[derive(Debug, Clone)]
 pub struct BufferInfo {
@@ -70,6 +138,10 @@ pub struct BufferInfo {
     pub modified: bool,
     /// Length of buffer in bytes
     pub length: usize,
+    /// Why the buffer's file can't be written; None if it can
+    pub read_only_reason: Option<ReadOnlyReason>,
+    /// Whether the buffer is pinned
+    pub pinned: bool,
 }
 
 /// Diff between current buffer content and last saved snapshot
@@ -93,6 +165,78 @@ pub struct ViewportInfo {
     pub height: u16,
 }
 
+/// Information about the host terminal
+#[derive(Debug, Clone, Default)]
+pub struct TerminalInfo {
+    /// Terminal width in cells
+    pub width: u16,
+    /// Terminal height in cells
+    pub height: u16,
+    /// Whether 24-bit color is available
+    pub true_color: bool,
+    /// Width of East Asian ambiguous characters: "narrow" or "wide"
+    pub unicode_width_ambiguous: String,
+}
+
+/// State of the active prompt (minibuffer)
+#[derive(Debug, Clone)]
+pub struct PromptInfo {
+    /// Custom type identifier for plugin prompts, None for built-in prompts
+    pub prompt_type: Option<String>,
+    /// Label displayed before the input
+    pub label: String,
+    /// Text typed so far
+    pub text: String,
+    /// Cursor position in the input (byte offset)
+    pub cursor: usize,
+    /// Index of the highlighted suggestion, if any
+    pub selected_suggestion: Option<usize>,
+}
+
+/// State of the file explorer tree
+#[derive(Debug, Clone, PartialEq)]
+pub struct FileExplorerInfo {
+    /// Whether the explorer panel is shown
+    pub visible: bool,
+    /// Directory at the root of the tree
+    pub root: PathBuf,
+    /// Selected file or directory, if any
+    pub selected_path: Option<PathBuf>,
+    /// Whether the selection is a directory
+    pub selected_is_dir: bool,
+    /// Directories currently expanded in the tree, including the root
+    pub expanded_paths: Vec<PathBuf>,
+}
+
+/// Entries of the file explorer a plugin's context menu action is offered on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExplorerActionTarget {
+    File,
+    Dir,
+    Any,
+}
+
+impl ExplorerActionTarget {
+    /// Parse "file", "dir" or "any"
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "file" => Some(Self::File),
+            "dir" => Some(Self::Dir),
+            "any" => Some(Self::Any),
+            _ => None,
+        }
+    }
+
+    /// Whether the action is offered on a file (or, with `is_dir`, a directory)
+    pub fn matches(self, is_dir: bool) -> bool {
+        match self {
+            Self::File => !is_dir,
+            Self::Dir => is_dir,
+            Self::Any => true,
+        }
+    }
+}
+
 /// Layout hints supplied by plugins (e.g., Compose mode)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LayoutHints {
@@ -169,19 +313,24 @@ pub struct EditorStateSnapshot {
     pub active_buffer_id: BufferId,
     /// Currently active split ID
     pub active_split_id: usize,
+    /// Splits whose input is locked (see [`PluginCommand::SetSplitLocked`])
+    pub locked_splits: Vec<usize>,
     /// Information about all open buffers
     pub buffers: HashMap<BufferId, BufferInfo>,
     /// Diff vs last saved snapshot for each buffer (line counts may be unknown)
     pub buffer_saved_diffs: HashMap<BufferId, BufferSavedDiff>,
     /// Primary cursor position for the active buffer
     pub primary_cursor: Option<CursorInfo>,
+    /// Line of the primary cursor (0-indexed, estimated for large files)
+    pub primary_cursor_line: Option<usize>,
     /// All cursor positions for the active buffer
     pub all_cursors: Vec<CursorInfo>,
     /// Viewport information for the active buffer
     pub viewport: Option<ViewportInfo>,
     /// Cursor positions per buffer (for buffers other than active)
     pub buffer_cursor_positions: HashMap<BufferId, usize>,
-    /// Text properties per buffer (for virtual buffers with properties)
+    /// Text properties at the primary cursor of each buffer that has any
+    /// there (for virtual buffers with properties)
     pub buffer_text_properties:
         HashMap<BufferId, Vec<crate::primitives::text_property::TextProperty>>,
     /// Selected text from the primary cursor (if any selection exists)
@@ -194,15 +343,27 @@ pub struct EditorStateSnapshot {
     /// LSP diagnostics per file URI
     /// Maps file URI string to Vec of diagnostics for that file
     pub diagnostics: HashMap<String, Vec<lsp_types::Diagnostic>>,
+    /// Diagnostic counts of the workspace and of each file
+    pub diagnostics_summary: DiagnosticsSummary,
     /// Runtime config as serde_json::Value (merged user config + defaults)
     /// This is the runtime config, not just the user's config file
     pub config: serde_json::Value,
     /// User config as serde_json::Value (only what's in the user's config file)
     /// Fields not present here are using default values
     pub user_config: serde_json::Value,
+    /// Colors of the active theme by name, as RGB
+    pub theme_colors: HashMap<String, (u8, u8, u8)>,
     /// Global editor mode for modal editing (e.g., "vi-normal", "vi-insert")
     /// When set, this mode's keybindings take precedence over normal key handling
     pub editor_mode: Option<String>,
+    /// Terminal size and capabilities
+    pub terminal: TerminalInfo,
+    /// Active prompt, if one is open
+    pub prompt: Option<PromptInfo>,
+    /// File explorer tree, once the explorer has been created
+    pub file_explorer: Option<FileExplorerInfo>,
+    /// Recently used project directories, pinned first
+    pub recent_projects: Vec<crate::services::recent_projects::RecentProject>,
 }
 
 impl EditorStateSnapshot {
@@ -210,9 +371,11 @@ impl EditorStateSnapshot {
         Self {
             active_buffer_id: BufferId(0),
             active_split_id: 0,
+            locked_splits: Vec::new(),
             buffers: HashMap::new(),
             buffer_saved_diffs: HashMap::new(),
             primary_cursor: None,
+            primary_cursor_line: None,
             all_cursors: Vec::new(),
             viewport: None,
             buffer_cursor_positions: HashMap::new(),
@@ -221,9 +384,15 @@ impl EditorStateSnapshot {
             clipboard: String::new(),
             working_dir: std::env::current_dir().unwrap_or_else(|_| PathBuf::from(".")),
             diagnostics: HashMap::new(),
+            diagnostics_summary: DiagnosticsSummary::default(),
             config: serde_json::Value::Null,
             user_config: serde_json::Value::Null,
+            theme_colors: HashMap::new(),
             editor_mode: None,
+            terminal: TerminalInfo::default(),
+            prompt: None,
+            file_explorer: None,
+            recent_projects: Vec::new(),
         }
     }
 }
@@ -257,13 +426,29 @@ pub enum PluginCommand {
         range: Range<usize>,
     },
 
+    /// Replace ranges of a buffer with new text, all or none, as one undo step
+    /// Ranges are byte offsets in the buffer as it is before any of them apply.
+    ApplyEdits {
+        buffer_id: BufferId,
+        edits: Vec<(Range<usize>, String)>,
+        /// Name of the undo step in undo and redo messages
+        label: Option<String>,
+    },
+
+    /// End a `plugin:` save action started through the SaveAction hook
+    FinishSaveAction {
+        request_id: u32,
+        /// Why the step failed, None if it succeeded
+        error: Option<String>,
+    },
+
     /// Add an overlay to a buffer, returns handle via response channel
     AddOverlay {
         buffer_id: BufferId,
         namespace: Option<OverlayNamespace>,
         range: Range<usize>,
-        color: (u8, u8, u8),
-        bg_color: Option<(u8, u8, u8)>,
+        color: DecorationColor,
+        bg_color: Option<DecorationColor>,
         underline: bool,
         bold: bool,
         italic: bool,
@@ -283,11 +468,27 @@ pub enum PluginCommand {
     ApplyTheme { theme_name: String },
 
     /// Reload configuration from file
-    /// After a plugin saves config changes, it should call this to reload the config
+    /// Use this after the config file was changed outside the editor
     ReloadConfig,
 
+    /// Set a single config value and persist it to the user config file
+    /// Goes through the editor's config persistence, so the write is atomic,
+    /// backed up, and refused if the file changed on disk since it was loaded
+    SetConfigValue {
+        /// Dot-separated key path into the config (e.g., "editor.tab_size")
+        path: String,
+        /// New value for the key
+        value: Value,
+    },
+
     /// Register a custom command
-    RegisterCommand { command: Command },
+    RegisterCommand {
+        command: Command,
+        /// The command's action honors `dry_run` in its options
+        dry_run: bool,
+        /// Parameters collected before the action runs
+        args: Vec<CommandArg>,
+    },
 
     /// Unregister a command by name
     UnregisterCommand { name: String },
@@ -317,6 +518,13 @@ pub enum PluginCommand {
     /// Enable/disable line numbers for a buffer
     SetLineNumbers { buffer_id: BufferId, enabled: bool },
 
+    /// Let the editor close a plugin's buffer once it has been hidden and
+    /// unused for a while (the default), or keep it
+    SetBufferIdleClose { buffer_id: BufferId, enabled: bool },
+
+    /// Pin or unpin a buffer, keeping it open through bulk closes
+    SetBufferPinned { buffer_id: BufferId, pinned: bool },
+
     /// Submit a transformed view stream for a viewport
     SubmitViewTransform {
         buffer_id: BufferId,
@@ -354,7 +562,7 @@ pub enum PluginCommand {
         virtual_text_id: String,
         position: usize,
         text: String,
-        color: (u8, u8, u8),
+        color: DecorationColor,
         use_bg: bool, // true = use color as background, false = use as foreground
         before: bool, // true = before char, false = after char
     },
@@ -380,10 +588,10 @@ pub enum PluginCommand {
         position: usize,
         /// Full line content to display
         text: String,
-        /// Foreground color (RGB)
-        fg_color: (u8, u8, u8),
-        /// Background color (RGB), None = transparent
-        bg_color: Option<(u8, u8, u8)>,
+        /// Foreground color
+        fg_color: DecorationColor,
+        /// Background color, None = transparent
+        bg_color: Option<DecorationColor>,
         /// true = above the line containing position, false = below
         above: bool,
         /// Namespace for bulk removal (e.g., "git-blame")
@@ -402,8 +610,8 @@ pub enum PluginCommand {
     /// Refresh lines for a buffer (clear seen_lines cache to re-trigger lines_changed hook)
     RefreshLines { buffer_id: BufferId },
 
-    /// Set a line indicator in the gutter's indicator column
-    /// Used for git gutter, breakpoints, bookmarks, etc.
+    /// Set a line indicator in the gutter's indicator column or a plugin's column
+    /// Used for git gutter, breakpoints, bookmarks, test coverage, etc.
     SetLineIndicator {
         buffer_id: BufferId,
         /// Line number (0-indexed)
@@ -412,12 +620,27 @@ pub enum PluginCommand {
         namespace: String,
         /// Symbol to display (e.g., "│", "●", "★")
         symbol: String,
-        /// Color as RGB tuple
-        color: (u8, u8, u8),
+        /// Color of the symbol
+        color: DecorationColor,
         /// Priority for display when multiple indicators exist (higher wins)
         priority: i32,
+        /// Gutter column registered with RegisterGutterColumn; `None` for the
+        /// indicator column
+        column: Option<String>,
+    },
+
+    /// Add a named column to the gutter of every buffer
+    /// Its cells show the line indicators set for the column.
+    RegisterGutterColumn {
+        name: String,
+        /// Width in cells
+        width: usize,
+        alignment: GutterAlignment,
     },
 
+    /// Remove a column added with RegisterGutterColumn
+    UnregisterGutterColumn { name: String },
+
     /// Clear all line indicators for a specific namespace
     ClearLineIndicators {
         buffer_id: BufferId,
@@ -462,6 +685,14 @@ pub enum PluginCommand {
         suggestions: Vec<crate::input::commands::Suggestion>,
     },
 
+    /// Cancel the active plugin prompt
+    /// Only closes a prompt whose type matches `prompt_type` (any plugin prompt if None),
+    /// so a stale request never closes a prompt the user opened in the meantime
+    ClosePrompt { prompt_type: Option<String> },
+
+    /// Submit the active plugin prompt as if the user picked the suggestion at `index`
+    AcceptPromptSuggestion { index: usize },
+
     /// Add a menu item to an existing menu
     AddMenuItem {
         menu_label: String,
@@ -512,6 +743,8 @@ pub enum PluginCommand {
         show_cursors: bool,
         /// Whether editing is disabled (blocks editing commands)
         editing_disabled: bool,
+        /// Whether to turn ANSI color codes in the entries into colors
+        parse_ansi: bool,
         /// Optional request ID for async response
         request_id: Option<u64>,
     },
@@ -539,8 +772,14 @@ pub enum PluginCommand {
         show_cursors: bool,
         /// Whether editing is disabled for this buffer (default false)
         editing_disabled: bool,
+        /// Whether to turn ANSI color codes in the entries into colors
+        parse_ansi: bool,
         /// Whether line wrapping is enabled for this split (None = use global setting)
         line_wrap: Option<bool>,
+        /// Whether the panel is recreated when the session is restored (needs panel_id)
+        persistent: bool,
+        /// Plugin that created the panel (receives the panel_restore hook)
+        plugin: Option<String>,
         /// Optional request ID for async response (if set, editor will send back buffer ID)
         request_id: Option<u64>,
     },
@@ -552,6 +791,13 @@ pub enum PluginCommand {
         entries: Vec<crate::primitives::text_property::TextPropertyEntry>,
     },
 
+    /// Add entries at the end of a virtual buffer's content
+    AppendVirtualBufferContent {
+        buffer_id: BufferId,
+        /// Entries with text and embedded properties
+        entries: Vec<crate::primitives::text_property::TextPropertyEntry>,
+    },
+
     /// Get text properties at the cursor position in a buffer
     GetTextPropertiesAtCursor { buffer_id: BufferId },
 
@@ -561,6 +807,8 @@ pub enum PluginCommand {
         parent: Option<String>,
         bindings: Vec<(String, String)>, // (key_string, command_name)
         read_only: bool,
+        /// Plugin defining the mode, shown as the source of its bindings
+        plugin: Option<String>,
     },
 
     /// Switch the current split to display a buffer
@@ -584,12 +832,27 @@ pub enum PluginCommand {
         show_cursors: bool,
         /// Whether editing is disabled for this buffer (default false)
         editing_disabled: bool,
+        /// Whether to turn ANSI color codes in the entries into colors
+        parse_ansi: bool,
         /// Whether line wrapping is enabled for this split (None = use global setting)
         line_wrap: Option<bool>,
         /// Optional request ID for async response
         request_id: Option<u64>,
     },
 
+    /// Create an ordinary untitled buffer in the active split, as the user
+    /// would with a new file
+    CreateBuffer {
+        /// Initial text; the buffer starts modified unless it's empty
+        content: String,
+        /// Key of the `languages` config picking the highlighting
+        language: Option<String>,
+        /// Name shown in the tab and buffer switcher until it's saved
+        name_hint: Option<String>,
+        /// Request ID for async response
+        request_id: u64,
+    },
+
     /// Close a buffer and remove it from all splits
     CloseBuffer { buffer_id: BufferId },
 
@@ -622,6 +885,24 @@ pub enum PluginCommand {
         ratio: f32,
     },
 
+    /// Lock or unlock input to a split: edits typed while it is focused are
+    /// refused, edits made through the plugin API are not
+    SetSplitLocked { split_id: SplitId, locked: bool },
+
+    /// Show text in a split's winbar, replacing the segment with the same id;
+    /// empty text removes it
+    SetWinbarSegment {
+        split_id: SplitId,
+        /// Identifies the segment among the split's segments
+        id: String,
+        text: String,
+        /// Color of the text, None for the winbar's own
+        color: Option<DecorationColor>,
+        /// Segments with a lower priority are dropped first when the winbar
+        /// is too narrow
+        priority: i32,
+    },
+
     /// Distribute splits evenly - make all given splits equal size
     DistributeSplitsEvenly {
         /// Split IDs to distribute evenly
@@ -635,6 +916,13 @@ pub enum PluginCommand {
         position: usize,
     },
 
+    /// Go to a byte offset in a buffer, making it the active buffer
+    GotoByte {
+        buffer_id: BufferId,
+        /// Byte offset, clamped to the buffer and snapped to a char boundary
+        offset: usize,
+    },
+
     /// Send an arbitrary LSP request and return the raw JSON response
     SendLspRequest {
         language: String,
@@ -678,7 +966,34 @@ pub enum PluginCommand {
         actions: Vec<ActionSpec>,
     },
 
+    /// Execute an editor action by name and report its outcome
+    /// Responds with ActionsExecuted once the editor has applied the action
+    ExecuteActionWithResult {
+        /// Action name (e.g., "move_word_right", "move_line_end")
+        action_name: String,
+        /// Request ID for async response
+        request_id: u64,
+    },
+
+    /// Execute multiple actions in sequence and report each one's outcome
+    /// Stops at the first failing action; later actions are not run and get no result
+    ExecuteActionsWithResult {
+        /// List of actions to execute in sequence
+        actions: Vec<ActionSpec>,
+        /// Request ID for async response
+        request_id: u64,
+    },
+
+    /// Get the effective keymap of a context, with the source of each binding
+    GetKeymap {
+        /// Context name as used in `when` clauses (None for the current context)
+        context: Option<String>,
+        /// Request ID for async response
+        request_id: u64,
+    },
+
     /// Get text from a buffer range (for yank operations)
+    /// Ranges longer than `editor.plugin_text_limit_bytes` are refused.
     GetBufferText {
         /// Buffer ID
         buffer_id: BufferId,
@@ -686,10 +1001,47 @@ pub enum PluginCommand {
         start: usize,
         /// End byte offset
         end: usize,
+        /// Plugin reading the text, charged for the bytes it receives
+        plugin: String,
+        /// Request ID for async response
+        request_id: u64,
+    },
+
+    /// Convert a byte offset to a line and column through the buffer's line index
+    ByteToLineCol {
+        /// Buffer ID
+        buffer_id: BufferId,
+        /// Byte offset
+        byte: usize,
+        /// Request ID for async response
+        request_id: u64,
+    },
+
+    /// Convert a line and column to a byte offset through the buffer's line index
+    LineColToByte {
+        /// Buffer ID
+        buffer_id: BufferId,
+        /// Line (0-indexed)
+        line: usize,
+        /// Column in bytes, clamped to the end of the line
+        column: usize,
         /// Request ID for async response
         request_id: u64,
     },
 
+    /// Send a buffer's whole content to a plugin in pieces
+    /// Pieces are delivered through the hook named by `buffer_chunks_hook_name`.
+    ReadBufferChunks {
+        /// Buffer ID
+        buffer_id: BufferId,
+        /// Most bytes in one piece
+        chunk_size: usize,
+        /// Id assigned by the plugin runtime
+        read_id: u32,
+        /// Plugin reading the text, charged for the bytes it receives
+        plugin: String,
+    },
+
     /// Set the global editor mode (for modal editing like vi mode)
     /// When set, the mode's keybindings take precedence over normal editing
     SetEditorMode {
@@ -697,6 +1049,20 @@ pub enum PluginCommand {
         mode: Option<String>,
     },
 
+    /// Set the terminal cursor style, overriding `ui.cursor` (for plugin modes
+    /// the config doesn't cover)
+    SetCursorStyle {
+        /// Style name (e.g., "steady_block") or None to go back to the config
+        style: Option<String>,
+    },
+
+    /// Keep (or stop keeping) a namespace's overlays when a buffer is reverted
+    /// with "Revert Buffer to Saved"; overlays in other namespaces are cleared
+    SetRevertStableNamespace {
+        namespace: OverlayNamespace,
+        stable: bool,
+    },
+
     /// Show an action popup with buttons for user interaction
     /// When the user selects an action, the ActionPopupResult hook is fired
     ShowActionPopup {
@@ -710,10 +1076,45 @@ pub enum PluginCommand {
         actions: Vec<ActionPopupAction>,
     },
 
+    /// Show a small borderless popup anchored to a byte position, below its
+    /// line (above when there is no room), for hover previews
+    /// Replaces the previous anchored popup; see `app::anchored_popup`.
+    ShowAnchoredPopup {
+        buffer_id: BufferId,
+        /// Byte offset the popup points at
+        position: usize,
+        lines: Vec<String>,
+        /// Widest the popup may be, in columns (lines wrap)
+        max_width: u16,
+        /// Close the popup after this long; `None` keeps it until the cursor
+        /// or mouse moves away
+        timeout_ms: Option<u64>,
+    },
+
+    /// Close the popup shown with ShowAnchoredPopup
+    CloseAnchoredPopup,
+
+    /// Show a toast notification above the status bar
+    /// Choosing a button fires the ActionPopupResult hook, as does the
+    /// notification going away unanswered (with "dismissed")
+    Notify {
+        /// Identifier (used in ActionPopupResult); a notification with the
+        /// same id is replaced
+        notification_id: String,
+        message: String,
+        severity: NotificationSeverity,
+        /// None for the default timeout, Some(0) to stay until dismissed
+        timeout_ms: Option<u64>,
+        /// Buttons to display
+        actions: Vec<ActionPopupAction>,
+    },
+
     /// Disable LSP for a specific language and persist to config
     DisableLspForLanguage {
         /// The language to disable LSP for (e.g., "python", "rust")
         language: String,
+        /// Name of the one server of the language to disable, or None for all
+        server: Option<String>,
     },
 
     /// Create a scroll sync group for anchor-based synchronized scrolling
@@ -742,6 +1143,398 @@ pub enum PluginCommand {
         /// The group ID returned by CreateScrollSyncGroup
         group_id: u32,
     },
+
+    /// Compare two open buffers side by side ("Compare Buffer With…")
+    CompareBuffers { left: BufferId, right: BufferId },
+
+    /// Open the pending edits review buffer for a set of proposed edits
+    /// Responds with PendingEditsReviewed once the user applies or cancels
+    ReviewPendingEdits {
+        /// Title shown in the review header (e.g., "Replace 'foo' with 'bar'")
+        title: String,
+        /// Proposed edits; `enabled` sets the initial checkbox state
+        edits: Vec<PendingEdit>,
+        /// Whether confirming applies the selected edits (false: only report the selection)
+        apply: bool,
+        /// Request ID for async response
+        request_id: u64,
+    },
+
+    /// Start reporting changes on disk to a path or glob
+    /// Changes are delivered through the hook named by `path_watch_hook_name`.
+    WatchPath {
+        /// Id assigned by the plugin runtime
+        watch_id: u32,
+        /// Plugin that owns the watch
+        plugin: String,
+        /// Path or glob, relative to the working directory unless absolute
+        pattern: String,
+    },
+
+    /// Stop a watch started with WatchPath
+    UnwatchPath { watch_id: u32 },
+
+    /// Show a long operation's progress until FinishProgress
+    /// Cancellation is delivered through the hook named by `progress_cancel_hook_name`.
+    StartProgress {
+        /// Id assigned by the plugin runtime
+        progress_id: u32,
+        /// Plugin that owns the entry
+        plugin: String,
+        title: String,
+        /// Whether the user may cancel it
+        cancellable: bool,
+    },
+
+    /// Change the percentage and/or message of a progress entry
+    UpdateProgress {
+        progress_id: u32,
+        /// 0-100; None keeps the current one
+        percentage: Option<u8>,
+        /// None keeps the current one
+        message: Option<String>,
+    },
+
+    /// Remove a progress entry
+    FinishProgress {
+        progress_id: u32,
+        /// Its plugin was unloaded before finishing it
+        abandoned: bool,
+    },
+
+    /// Expand the ancestors of a path in the file explorer and select it
+    /// Relative paths are taken from the working directory.
+    RevealInFileExplorer { path: PathBuf },
+
+    /// Show a different directory at the root of the file explorer
+    /// Checked like a project switch: the path must be an existing directory.
+    SetFileExplorerRoot { path: PathBuf },
+
+    /// Add an entry to the file explorer's context menu, or change one
+    /// registered before
+    /// Choosing it runs the hook named by `explorer_action_hook_name`.
+    RegisterExplorerAction {
+        id: String,
+        label: String,
+        /// Entries the action is offered on
+        when: ExplorerActionTarget,
+        /// Plugin that owns the action
+        plugin: String,
+    },
+
+    /// Remove an entry added with RegisterExplorerAction
+    UnregisterExplorerAction { id: String },
+
+    /// A plugin's hook handler set itself off too often or too deep and was
+    /// cut off for the rest of its chain (sent by the plugin thread)
+    HookLoopCut {
+        plugin: String,
+        hook: String,
+        buffer_id: Option<BufferId>,
+        /// Depth of the chain when it was cut off
+        depth: u32,
+        /// Runs of the handler the chain caused
+        runs: u32,
+    },
+}
+
+/// Buffer or split a plugin command acts on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PluginCommandTarget {
+    Buffer(BufferId),
+    Split(SplitId),
+}
+
+impl PluginCommand {
+    /// Name of the command and the buffer or split it acts on, for commands
+    /// that act on one
+    ///
+    /// The editor holds these back while their target is still being created.
+    pub fn target(&self) -> Option<(&'static str, PluginCommandTarget)> {
+        Some(match self {
+            Self::InsertText { buffer_id, .. } => {
+                ("InsertText", PluginCommandTarget::Buffer(*buffer_id))
+            }
+            Self::DeleteRange { buffer_id, .. } => {
+                ("DeleteRange", PluginCommandTarget::Buffer(*buffer_id))
+            }
+            Self::AddOverlay { buffer_id, .. } => {
+                ("AddOverlay", PluginCommandTarget::Buffer(*buffer_id))
+            }
+            Self::RemoveOverlay { buffer_id, .. } => {
+                ("RemoveOverlay", PluginCommandTarget::Buffer(*buffer_id))
+            }
+            Self::SetLayoutHints { buffer_id, .. } => {
+                ("SetLayoutHints", PluginCommandTarget::Buffer(*buffer_id))
+            }
+            Self::SetLineNumbers { buffer_id, .. } => {
+                ("SetLineNumbers", PluginCommandTarget::Buffer(*buffer_id))
+            }
+            Self::SetBufferIdleClose { buffer_id, .. } => (
+                "SetBufferIdleClose",
+                PluginCommandTarget::Buffer(*buffer_id),
+            ),
+            Self::SetBufferPinned { buffer_id, .. } => {
+                ("SetBufferPinned", PluginCommandTarget::Buffer(*buffer_id))
+            }
+            Self::SubmitViewTransform { buffer_id, .. } => (
+                "SubmitViewTransform",
+                PluginCommandTarget::Buffer(*buffer_id),
+            ),
+            Self::ClearViewTransform { buffer_id, .. } => (
+                "ClearViewTransform",
+                PluginCommandTarget::Buffer(*buffer_id),
+            ),
+            Self::ClearAllOverlays { buffer_id, .. } => {
+                ("ClearAllOverlays", PluginCommandTarget::Buffer(*buffer_id))
+            }
+            Self::ClearNamespace { buffer_id, .. } => {
+                ("ClearNamespace", PluginCommandTarget::Buffer(*buffer_id))
+            }
+            Self::ClearOverlaysInRange { buffer_id, .. } => (
+                "ClearOverlaysInRange",
+                PluginCommandTarget::Buffer(*buffer_id),
+            ),
+            Self::AddVirtualText { buffer_id, .. } => {
+                ("AddVirtualText", PluginCommandTarget::Buffer(*buffer_id))
+            }
+            Self::RemoveVirtualText { buffer_id, .. } => {
+                ("RemoveVirtualText", PluginCommandTarget::Buffer(*buffer_id))
+            }
+            Self::RemoveVirtualTextsByPrefix { buffer_id, .. } => (
+                "RemoveVirtualTextsByPrefix",
+                PluginCommandTarget::Buffer(*buffer_id),
+            ),
+            Self::ClearVirtualTexts { buffer_id, .. } => {
+                ("ClearVirtualTexts", PluginCommandTarget::Buffer(*buffer_id))
+            }
+            Self::AddVirtualLine { buffer_id, .. } => {
+                ("AddVirtualLine", PluginCommandTarget::Buffer(*buffer_id))
+            }
+            Self::ClearVirtualTextNamespace { buffer_id, .. } => (
+                "ClearVirtualTextNamespace",
+                PluginCommandTarget::Buffer(*buffer_id),
+            ),
+            Self::RefreshLines { buffer_id, .. } => {
+                ("RefreshLines", PluginCommandTarget::Buffer(*buffer_id))
+            }
+            Self::SetLineIndicator { buffer_id, .. } => {
+                ("SetLineIndicator", PluginCommandTarget::Buffer(*buffer_id))
+            }
+            Self::ClearLineIndicators { buffer_id, .. } => (
+                "ClearLineIndicators",
+                PluginCommandTarget::Buffer(*buffer_id),
+            ),
+            Self::SetVirtualBufferContent { buffer_id, .. } => (
+                "SetVirtualBufferContent",
+                PluginCommandTarget::Buffer(*buffer_id),
+            ),
+            Self::AppendVirtualBufferContent { buffer_id, .. } => (
+                "AppendVirtualBufferContent",
+                PluginCommandTarget::Buffer(*buffer_id),
+            ),
+            Self::GetTextPropertiesAtCursor { buffer_id, .. } => (
+                "GetTextPropertiesAtCursor",
+                PluginCommandTarget::Buffer(*buffer_id),
+            ),
+            Self::ShowBuffer { buffer_id, .. } => {
+                ("ShowBuffer", PluginCommandTarget::Buffer(*buffer_id))
+            }
+            Self::CloseBuffer { buffer_id, .. } => {
+                ("CloseBuffer", PluginCommandTarget::Buffer(*buffer_id))
+            }
+            Self::SetSplitBuffer { buffer_id, .. } => {
+                ("SetSplitBuffer", PluginCommandTarget::Buffer(*buffer_id))
+            }
+            Self::RequestHighlights { buffer_id, .. } => {
+                ("RequestHighlights", PluginCommandTarget::Buffer(*buffer_id))
+            }
+            Self::SetBufferCursor { buffer_id, .. } => {
+                ("SetBufferCursor", PluginCommandTarget::Buffer(*buffer_id))
+            }
+            Self::GotoByte { buffer_id, .. } => {
+                ("GotoByte", PluginCommandTarget::Buffer(*buffer_id))
+            }
+            Self::GetBufferText { buffer_id, .. } => {
+                ("GetBufferText", PluginCommandTarget::Buffer(*buffer_id))
+            }
+            Self::ByteToLineCol { buffer_id, .. } => {
+                ("ByteToLineCol", PluginCommandTarget::Buffer(*buffer_id))
+            }
+            Self::LineColToByte { buffer_id, .. } => {
+                ("LineColToByte", PluginCommandTarget::Buffer(*buffer_id))
+            }
+            Self::ReadBufferChunks { buffer_id, .. } => {
+                ("ReadBufferChunks", PluginCommandTarget::Buffer(*buffer_id))
+            }
+            Self::FocusSplit { split_id, .. } => {
+                ("FocusSplit", PluginCommandTarget::Split(*split_id))
+            }
+            Self::SetSplitScroll { split_id, .. } => {
+                ("SetSplitScroll", PluginCommandTarget::Split(*split_id))
+            }
+            Self::CloseSplit { split_id, .. } => {
+                ("CloseSplit", PluginCommandTarget::Split(*split_id))
+            }
+            Self::SetSplitRatio { split_id, .. } => {
+                ("SetSplitRatio", PluginCommandTarget::Split(*split_id))
+            }
+            Self::SetSplitLocked { split_id, .. } => {
+                ("SetSplitLocked", PluginCommandTarget::Split(*split_id))
+            }
+            Self::SetWinbarSegment { split_id, .. } => {
+                ("SetWinbarSegment", PluginCommandTarget::Split(*split_id))
+            }
+            _ => return None,
+        })
+    }
+
+    /// Make the text this command puts on screen safe to draw
+    ///
+    /// Control characters become visible placeholders, single-line text
+    /// loses its line breaks and overlong text is cut, ending in
+    /// `ellipsis` (see [`crate::primitives::display_text`]). The editor
+    /// runs every plugin command through this before handling it, so a
+    /// new command carrying display text only needs an arm here. Ids,
+    /// values and other data are left alone.
+    pub fn sanitize_display_text(&mut self, ellipsis: &str) {
+        let line = |text: &mut String, max_chars| {
+            if let Cow::Owned(clean) = sanitize_line(text, max_chars, ellipsis) {
+                *text = clean;
+            }
+        };
+        let message = |text: &mut String| {
+            if let Cow::Owned(clean) = sanitize_multiline(text, MAX_MESSAGE_CHARS, ellipsis) {
+                *text = clean;
+            }
+        };
+        let entries = |entries: &mut Vec<crate::primitives::text_property::TextPropertyEntry>| {
+            for entry in entries {
+                if let Cow::Owned(clean) = sanitize_buffer_text(&entry.text) {
+                    entry.text = clean;
+                }
+            }
+        };
+        let actions = |actions: &mut Vec<ActionPopupAction>| {
+            for action in actions {
+                line(&mut action.label, MAX_LABEL_CHARS);
+            }
+        };
+        fn menu_item(item: &mut crate::config::MenuItem, line: &dyn Fn(&mut String, usize)) {
+            use crate::config::MenuItem;
+            match item {
+                MenuItem::Action { label, .. } | MenuItem::DynamicSubmenu { label, .. } => {
+                    line(label, MAX_LABEL_CHARS)
+                }
+                MenuItem::Submenu { label, items } => {
+                    line(label, MAX_LABEL_CHARS);
+                    for item in items {
+                        menu_item(item, line);
+                    }
+                }
+                MenuItem::Label { info } => line(info, MAX_LABEL_CHARS),
+                MenuItem::Separator { .. } => {}
+            }
+        }
+
+        match self {
+            Self::SetStatus { message } => line(message, MAX_STATUS_CHARS),
+            Self::AddVirtualText { text, .. } => line(text, MAX_INLINE_CHARS),
+            Self::AddVirtualLine { text, .. } => line(text, MAX_LINE_CHARS),
+            Self::SetLineIndicator { symbol, .. } => line(symbol, MAX_SYMBOL_CHARS),
+            Self::StartPrompt { label, .. } => line(label, MAX_LABEL_CHARS),
+            Self::StartPromptWithInitial {
+                label,
+                initial_value,
+                ..
+            } => {
+                line(label, MAX_LABEL_CHARS);
+                line(initial_value, MAX_LINE_CHARS);
+            }
+            Self::SetPromptSuggestions { suggestions } => {
+                for suggestion in suggestions {
+                    line(&mut suggestion.text, MAX_LINE_CHARS);
+                    if let Some(description) = &mut suggestion.description {
+                        line(description, MAX_LINE_CHARS);
+                    }
+                }
+            }
+            Self::CreateVirtualBuffer { name, .. } => line(name, MAX_LABEL_CHARS),
+            Self::CreateBuffer {
+                name_hint: Some(name),
+                ..
+            } => line(name, MAX_LABEL_CHARS),
+            Self::CreateVirtualBufferWithContent {
+                name,
+                entries: buffer_entries,
+                ..
+            }
+            | Self::CreateVirtualBufferInSplit {
+                name,
+                entries: buffer_entries,
+                ..
+            }
+            | Self::CreateVirtualBufferInExistingSplit {
+                name,
+                entries: buffer_entries,
+                ..
+            } => {
+                line(name, MAX_LABEL_CHARS);
+                entries(buffer_entries);
+            }
+            Self::SetVirtualBufferContent {
+                entries: buffer_entries,
+                ..
+            }
+            | Self::AppendVirtualBufferContent {
+                entries: buffer_entries,
+                ..
+            } => entries(buffer_entries),
+            Self::SetWinbarSegment { text, .. } => line(text, MAX_LABEL_CHARS),
+            Self::ShowActionPopup {
+                title,
+                message: text,
+                actions: popup_actions,
+                ..
+            } => {
+                line(title, MAX_LABEL_CHARS);
+                message(text);
+                actions(popup_actions);
+            }
+            Self::ShowAnchoredPopup { lines, .. } => {
+                for text in lines {
+                    line(text, MAX_LINE_CHARS);
+                }
+            }
+            Self::Notify {
+                message: text,
+                actions: notification_actions,
+                ..
+            } => {
+                message(text);
+                actions(notification_actions);
+            }
+            Self::RegisterCommand { command, .. } => {
+                line(&mut command.name, MAX_LABEL_CHARS);
+                line(&mut command.description, MAX_LINE_CHARS);
+            }
+            Self::UnregisterCommand { name } => line(name, MAX_LABEL_CHARS),
+            Self::AddMenuItem { item, .. } => menu_item(item, &line),
+            Self::AddMenu { menu, .. } => {
+                line(&mut menu.label, MAX_LABEL_CHARS);
+                for item in &mut menu.items {
+                    menu_item(item, &line);
+                }
+            }
+            Self::ReviewPendingEdits { title, .. } => line(title, MAX_LABEL_CHARS),
+            Self::StartProgress { title, .. } => line(title, MAX_LABEL_CHARS),
+            Self::UpdateProgress {
+                message: Some(text),
+                ..
+            } => line(text, MAX_LINE_CHARS),
+            _ => {}
+        }
+    }
 }
 
 /// Hunk status for Review Diff
@@ -883,8 +1676,8 @@ impl PluginApi {
             buffer_id,
             namespace: namespace.map(crate::view::overlay::OverlayNamespace::from_string),
             range,
-            color,
-            bg_color,
+            color: color.into(),
+            bg_color: bg_color.map(DecorationColor::from),
             underline,
             bold,
             italic,
@@ -973,6 +1766,26 @@ impl PluginApi {
         self.send_command(PluginCommand::SetPromptSuggestions { suggestions })
     }
 
+    /// Cancel the active plugin prompt if its type matches (any plugin prompt if None)
+    pub fn close_prompt(&self, prompt_type: Option<String>) -> Result<(), String> {
+        self.send_command(PluginCommand::ClosePrompt { prompt_type })
+    }
+
+    /// Submit the active plugin prompt with the suggestion at `index`
+    pub fn accept_prompt_suggestion(&self, index: usize) -> Result<(), String> {
+        self.send_command(PluginCommand::AcceptPromptSuggestion { index })
+    }
+
+    /// Expand the ancestors of a path in the file explorer and select it
+    pub fn reveal_in_file_explorer(&self, path: PathBuf) -> Result<(), String> {
+        self.send_command(PluginCommand::RevealInFileExplorer { path })
+    }
+
+    /// Show a different directory at the root of the file explorer
+    pub fn set_file_explorer_root(&self, path: PathBuf) -> Result<(), String> {
+        self.send_command(PluginCommand::SetFileExplorerRoot { path })
+    }
+
     /// Add a menu item to an existing menu
     pub fn add_menu_item(
         &self,
@@ -1048,6 +1861,7 @@ impl PluginApi {
             show_line_numbers: true,
             show_cursors: true,
             editing_disabled: false,
+            parse_ansi: false,
             request_id: None,
         })
     }
@@ -1063,6 +1877,17 @@ impl PluginApi {
         self.send_command(PluginCommand::SetVirtualBufferContent { buffer_id, entries })
     }
 
+    /// Add entries at the end of a virtual buffer's content
+    ///
+    /// Lets a panel with many entries send them in chunks.
+    pub fn append_virtual_buffer_content(
+        &self,
+        buffer_id: BufferId,
+        entries: Vec<crate::primitives::text_property::TextPropertyEntry>,
+    ) -> Result<(), String> {
+        self.send_command(PluginCommand::AppendVirtualBufferContent { buffer_id, entries })
+    }
+
     /// Get text properties at cursor position in a buffer
     ///
     /// This triggers a command that will make properties available to plugins.
@@ -1086,6 +1911,7 @@ impl PluginApi {
             parent,
             bindings,
             read_only,
+            plugin: None,
         })
     }
 
@@ -1277,7 +2103,7 @@ mod tests {
                 assert_eq!(buffer_id.0, 1);
                 assert_eq!(namespace.as_ref().map(|n| n.as_str()), Some("test-overlay"));
                 assert_eq!(range, 0..10);
-                assert_eq!(color, (255, 0, 0));
+                assert_eq!(color, DecorationColor::Rgb(255, 0, 0));
                 assert_eq!(bg_color, None);
                 assert!(underline);
                 assert!(!bold);
@@ -1343,6 +2169,8 @@ mod tests {
                 path: Some(std::path::PathBuf::from("/test/file.txt")),
                 modified: true,
                 length: 100,
+                read_only_reason: None,
+                pinned: false,
             };
             snapshot.buffers.insert(BufferId(1), buffer_info);
         }
@@ -1382,6 +2210,8 @@ mod tests {
                     path: Some(std::path::PathBuf::from("/file1.txt")),
                     modified: false,
                     length: 50,
+                    read_only_reason: None,
+                    pinned: false,
                 },
             );
             snapshot.buffers.insert(
@@ -1391,6 +2221,8 @@ mod tests {
                     path: Some(std::path::PathBuf::from("/file2.txt")),
                     modified: true,
                     length: 100,
+                    read_only_reason: None,
+                    pinned: false,
                 },
             );
             snapshot.buffers.insert(
@@ -1400,6 +2232,8 @@ mod tests {
                     path: None,
                     modified: false,
                     length: 0,
+                    read_only_reason: None,
+                    pinned: false,
                 },
             );
         }
@@ -0,0 +1,202 @@
+//! Guard rails for plugin hooks that set themselves off
+//!
+//! A hook handler can send commands whose effects emit hooks again, such as
+//! an `after_insert` handler inserting text. Such a chain of hooks, commands
+//! and the hooks they cause has a depth: hooks emitted while the editor
+//! applies the commands of a hook batch are one deeper than the batch, and
+//! every other hook starts a new chain at depth 0.
+//!
+//! The plugin thread checks each handler against the chain before running
+//! it. A handler is cut off for the rest of the chain, for its plugin, hook
+//! and buffer, once the chain is deeper than [`HookLoopLimits::max_depth`] or
+//! the handler has run more than [`HookLoopLimits::max_repeats`] times within
+//! it. Only hooks caused by plugin commands count towards the repeats, so a
+//! handler that reacts to each of many user edits, or once to every save, is
+//! never cut off.
+
+use crate::model::event::BufferId;
+use std::collections::{HashMap, HashSet};
+
+/// Default deepest chain of hooks caused by plugin commands
+pub const DEFAULT_HOOK_CHAIN_DEPTH: u32 = 8;
+
+/// Default most runs of one handler for one buffer caused within a chain
+pub const DEFAULT_HOOK_REPEAT_LIMIT: u32 = 256;
+
+/// Limits of a chain of hooks caused by plugin commands
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HookLoopLimits {
+    /// Deepest a chain can go; hooks deeper than this don't run
+    pub max_depth: u32,
+    /// Most runs of one plugin's handler of a hook for one buffer caused
+    /// within a chain
+    pub max_repeats: u32,
+}
+
+impl Default for HookLoopLimits {
+    fn default() -> Self {
+        Self {
+            max_depth: DEFAULT_HOOK_CHAIN_DEPTH,
+            max_repeats: DEFAULT_HOOK_REPEAT_LIMIT,
+        }
+    }
+}
+
+/// A handler cut off for the rest of its chain
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HookLoopCut {
+    /// Plugin that registered the handler
+    pub plugin: String,
+    pub hook: String,
+    /// The buffer the hook is about, for hooks about one
+    pub buffer_id: Option<BufferId>,
+    /// Depth of the chain when the handler was cut off
+    pub depth: u32,
+    /// Runs of the handler the chain caused, counting the one refused
+    pub runs: u32,
+}
+
+/// Whether a handler runs, see [`HookLoopGuard::admit`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HookAdmission {
+    Run,
+    /// Cut off now, to be reported
+    Cut(HookLoopCut),
+    /// Cut off earlier in the chain
+    Dropped,
+}
+
+/// (plugin, hook, buffer) of a handler run
+type HandlerKey = (String, String, Option<BufferId>);
+
+/// Runs of each handler within the current chain of hooks
+#[derive(Debug, Default)]
+pub struct HookLoopGuard {
+    runs: HashMap<HandlerKey, u32>,
+    cut: HashSet<HandlerKey>,
+}
+
+impl HookLoopGuard {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start running a batch of hooks at `depth`; a batch at depth 0 starts
+    /// a new chain, clearing the runs and cuts of the last one
+    pub fn start_batch(&mut self, depth: u32) {
+        if depth == 0 {
+            self.runs.clear();
+            self.cut.clear();
+        }
+    }
+
+    /// Whether `plugin`'s handler of `hook` about `buffer_id` runs in a batch
+    /// at `depth`, counting the run
+    pub fn admit(
+        &mut self,
+        plugin: &str,
+        hook: &str,
+        buffer_id: Option<BufferId>,
+        depth: u32,
+        limits: HookLoopLimits,
+    ) -> HookAdmission {
+        let key = (plugin.to_string(), hook.to_string(), buffer_id);
+        if self.cut.contains(&key) {
+            return HookAdmission::Dropped;
+        }
+        let runs = if depth > 0 {
+            let runs = self.runs.entry(key.clone()).or_default();
+            *runs += 1;
+            *runs
+        } else {
+            0
+        };
+        if depth <= limits.max_depth && runs <= limits.max_repeats {
+            return HookAdmission::Run;
+        }
+        self.cut.insert(key);
+        HookAdmission::Cut(HookLoopCut {
+            plugin: plugin.to_string(),
+            hook: hook.to_string(),
+            buffer_id,
+            depth,
+            runs,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LIMITS: HookLoopLimits = HookLoopLimits {
+        max_depth: 3,
+        max_repeats: 5,
+    };
+
+    fn admit(guard: &mut HookLoopGuard, plugin: &str, depth: u32) -> HookAdmission {
+        guard.admit(plugin, "after_insert", Some(BufferId(1)), depth, LIMITS)
+    }
+
+    #[test]
+    fn test_chain_is_cut_below_max_depth() {
+        let mut guard = HookLoopGuard::new();
+        for depth in 0..=LIMITS.max_depth {
+            guard.start_batch(depth);
+            assert_eq!(admit(&mut guard, "loop", depth), HookAdmission::Run);
+        }
+        guard.start_batch(4);
+        assert_eq!(
+            admit(&mut guard, "loop", 4),
+            HookAdmission::Cut(HookLoopCut {
+                plugin: "loop".to_string(),
+                hook: "after_insert".to_string(),
+                buffer_id: Some(BufferId(1)),
+                depth: 4,
+                runs: 4,
+            })
+        );
+        // Reported once, then dropped for the rest of the chain
+        assert_eq!(admit(&mut guard, "loop", 4), HookAdmission::Dropped);
+        assert_eq!(admit(&mut guard, "loop", 2), HookAdmission::Dropped);
+
+        // A new chain runs it again
+        guard.start_batch(0);
+        assert_eq!(admit(&mut guard, "loop", 0), HookAdmission::Run);
+    }
+
+    #[test]
+    fn test_repeats_are_counted_per_plugin_hook_and_buffer() {
+        let mut guard = HookLoopGuard::new();
+        guard.start_batch(1);
+        for _ in 0..LIMITS.max_repeats {
+            assert_eq!(admit(&mut guard, "loop", 1), HookAdmission::Run);
+        }
+        assert!(matches!(
+            admit(&mut guard, "loop", 1),
+            HookAdmission::Cut(HookLoopCut { runs: 6, .. })
+        ));
+
+        // Other plugins, hooks and buffers still run
+        assert_eq!(admit(&mut guard, "other", 1), HookAdmission::Run);
+        assert_eq!(
+            guard.admit("loop", "cursor_moved", Some(BufferId(1)), 1, LIMITS),
+            HookAdmission::Run
+        );
+        assert_eq!(
+            guard.admit("loop", "after_insert", Some(BufferId(2)), 1, LIMITS),
+            HookAdmission::Run
+        );
+    }
+
+    #[test]
+    fn test_hooks_that_start_a_chain_are_not_counted() {
+        let mut guard = HookLoopGuard::new();
+        guard.start_batch(0);
+        // Like a handler reacting to each of many user edits
+        for _ in 0..100 {
+            assert_eq!(admit(&mut guard, "formatter", 0), HookAdmission::Run);
+        }
+        assert_eq!(admit(&mut guard, "formatter", 1), HookAdmission::Run);
+    }
+}
@@ -7,6 +7,7 @@ use anyhow::{anyhow, Result};
 
 use crate::input::keybindings::Action;
 use crate::model::event::{BufferId, CursorId, SplitId};
+use crate::services::path_watch::PathChangeKind;
 use crate::services::plugins::api::{ViewTokenWire, ViewTokenWireKind};
 use std::collections::HashMap;
 use std::ops::Range;
@@ -27,6 +28,9 @@ pub enum HookArgs {
     /// After a buffer is successfully saved
     AfterFileSave { buffer_id: BufferId, path: PathBuf },
 
+    /// A buffer was replaced with its file's contents by "Revert Buffer to Saved"
+    BufferReverted { buffer_id: BufferId, path: PathBuf },
+
     /// A buffer was closed
     BufferClosed { buffer_id: BufferId },
 
@@ -213,6 +217,21 @@ pub enum HookArgs {
         content_y: u16,
     },
 
+    /// Mouse moved over text in a buffer
+    /// Fires at most every 50ms, and only when the mouse is over a character
+    /// of the buffer (not the gutter, virtual text or past the end of a line).
+    /// Plugins use it with showAnchoredPopup for hover previews.
+    MouseMovedInBuffer {
+        buffer_id: BufferId,
+        split_id: SplitId,
+        /// Byte offset of the character under the mouse
+        byte_offset: usize,
+        /// Screen row of the mouse
+        screen_row: u16,
+        /// Screen column of the mouse
+        screen_col: u16,
+    },
+
     /// LSP server request (server -> client)
     /// This hook fires when the LSP server sends a custom request method
     /// that isn't handled by the core. Plugins can use this to handle
@@ -237,6 +256,9 @@ pub enum HookArgs {
         height: u16,
     },
 
+    /// Terminal was resized (from a resize event or the fallback size poll)
+    TerminalResized { width: u16, height: u16 },
+
     /// LSP server failed to start or crashed
     /// This hook fires when an LSP server encounters an error during startup
     /// or unexpectedly terminates. Plugins can use this to offer installation
@@ -244,6 +266,8 @@ pub enum HookArgs {
     LspServerError {
         /// The language that failed (e.g., "python", "rust")
         language: String,
+        /// Name of the server that failed, when the language has several
+        server: Option<String>,
         /// The server command that failed (e.g., "pylsp", "rust-analyzer")
         server_command: String,
         /// Error type: "not_found", "spawn_failed", "timeout", "crash"
@@ -262,6 +286,17 @@ pub enum HookArgs {
         has_error: bool,
     },
 
+    /// User clicked a gutter column
+    /// Fires for the built-in columns and for columns registered by plugins.
+    GutterClicked {
+        buffer_id: BufferId,
+        /// 0-indexed line that was clicked
+        line: usize,
+        /// Name of the clicked column ("indicators", "line_numbers" or a
+        /// plugin column)
+        column: String,
+    },
+
     /// User selected an action from an action popup
     /// This hook fires when the user selects an action or dismisses a popup
     /// created with showActionPopup.
@@ -271,6 +306,198 @@ pub enum HookArgs {
         /// The action ID selected, or "dismissed" if closed without selection
         action_id: String,
     },
+
+    /// A persistent panel was recreated from the saved session
+    /// The panel shows a placeholder until its plugin sets the content again.
+    /// Plugins should ignore panels owned by another plugin.
+    PanelRestore {
+        /// Plugin that created the panel
+        plugin: String,
+        /// The panel_id passed to createVirtualBufferInSplit
+        panel_id: String,
+        /// The placeholder buffer to fill
+        buffer_id: BufferId,
+        /// The split showing the panel
+        split_id: Option<SplitId>,
+    },
+
+    /// A path watched with watchPath was created, modified or removed
+    /// Delivered only to the handler of that watch, see [`path_watch_hook_name`].
+    PathChanged {
+        /// The id returned by watchPath
+        watch_id: u32,
+        /// The file that changed
+        path: PathBuf,
+        /// What happened to it
+        kind: PathChangeKind,
+    },
+
+    /// A piece of a buffer read with readBufferChunks
+    /// Delivered only to the handler of that read, see [`buffer_chunks_hook_name`].
+    BufferChunk {
+        /// The id returned by readBufferChunks
+        read_id: u32,
+        buffer_id: BufferId,
+        /// Byte offset of the piece in the buffer
+        offset: usize,
+        text: String,
+        /// This is the last piece
+        done: bool,
+    },
+
+    /// The selected entry of the file explorer changed
+    FileExplorerSelectionChanged {
+        /// The selected file or directory, None when nothing is selected
+        path: Option<PathBuf>,
+        /// Whether the selection is a directory
+        is_dir: bool,
+    },
+
+    /// The active theme was switched or its file reloaded
+    ThemeChanged {
+        /// Name of the active theme
+        name: String,
+    },
+
+    /// The user cancelled a progress entry started with startProgress
+    /// Delivered only to the handler of that entry, see [`progress_cancel_hook_name`].
+    ProgressCancelled {
+        /// The id of the progress handle
+        progress_id: u32,
+    },
+
+    /// A `plugin:` step of a language's save actions is running
+    /// Delivered only to the handler of that step, see [`save_action_hook_name`].
+    /// The save waits until the plugin calls finishSaveAction with the request id.
+    SaveAction {
+        /// The id to pass to finishSaveAction
+        request_id: u32,
+        buffer_id: BufferId,
+        path: PathBuf,
+        /// The step as configured after "plugin:", e.g. "my_plugin.fix_headers"
+        action: String,
+    },
+
+    /// An action registered with registerExplorerAction was chosen from the
+    /// file explorer's context menu
+    /// Delivered only to the handler of that action, see [`explorer_action_hook_name`].
+    ExplorerAction {
+        /// The id the action was registered with
+        action_id: String,
+        /// The file or directory the menu was opened on
+        path: PathBuf,
+        is_dir: bool,
+    },
+
+    /// A split was divided in two
+    SplitCreated {
+        /// The new pane
+        split_id: SplitId,
+        /// The container now holding the new pane and the one divided
+        parent_id: SplitId,
+        /// "horizontal" (new pane below) or "vertical" (new pane to the right)
+        direction: String,
+        /// Whether a plugin command made the change
+        by_plugin: bool,
+    },
+
+    /// A split was closed
+    SplitClosed { split_id: SplitId, by_plugin: bool },
+
+    /// Another split became the active one
+    SplitFocused {
+        split_id: SplitId,
+        previous_split_id: SplitId,
+        by_plugin: bool,
+    },
+
+    /// The ratio of a split container changed; fired once at the end of a
+    /// separator drag
+    SplitResized {
+        /// The container
+        split_id: SplitId,
+        /// Share of the container its first (top or left) child takes
+        ratio: f32,
+        by_plugin: bool,
+    },
+
+    /// The whole layout was rebuilt from saved state, instead of reporting
+    /// each split change on the way
+    LayoutRestored {
+        /// "session", "snapshot" or "layout"
+        source: String,
+        active_split_id: SplitId,
+    },
+
+    /// A plugin edit command was refused and nothing was changed
+    EditRejected {
+        buffer_id: BufferId,
+        /// The refused command: "insert_text", "delete_range", "apply_edits",
+        /// "insert_at_cursor" or "delete_selection"
+        command: String,
+        /// Why: "protected" for a file matching `files.protected_globs` that
+        /// wasn't unlocked
+        reason: String,
+        /// The glob the file matched
+        rule: String,
+    },
+}
+
+impl HookArgs {
+    /// The buffer the hook is about, for hooks about one
+    pub fn buffer_id(&self) -> Option<BufferId> {
+        match self {
+            Self::AfterFileOpen { buffer_id, .. }
+            | Self::BeforeFileSave { buffer_id, .. }
+            | Self::AfterFileSave { buffer_id, .. }
+            | Self::BufferReverted { buffer_id, .. }
+            | Self::BufferClosed { buffer_id, .. }
+            | Self::BeforeInsert { buffer_id, .. }
+            | Self::AfterInsert { buffer_id, .. }
+            | Self::BeforeDelete { buffer_id, .. }
+            | Self::AfterDelete { buffer_id, .. }
+            | Self::CursorMoved { buffer_id, .. }
+            | Self::BufferActivated { buffer_id, .. }
+            | Self::BufferDeactivated { buffer_id, .. }
+            | Self::RenderStart { buffer_id, .. }
+            | Self::RenderLine { buffer_id, .. }
+            | Self::LinesChanged { buffer_id, .. }
+            | Self::ViewTransformRequest { buffer_id, .. }
+            | Self::MouseMovedInBuffer { buffer_id, .. }
+            | Self::ViewportChanged { buffer_id, .. }
+            | Self::GutterClicked { buffer_id, .. }
+            | Self::PanelRestore { buffer_id, .. }
+            | Self::BufferChunk { buffer_id, .. }
+            | Self::SaveAction { buffer_id, .. }
+            | Self::EditRejected { buffer_id, .. } => Some(*buffer_id),
+            _ => None,
+        }
+    }
+}
+
+/// Name of the hook that delivers the changes of one path watch
+pub fn path_watch_hook_name(watch_id: u32) -> String {
+    format!("path_watch:{}", watch_id)
+}
+
+/// Name of the hook that delivers the pieces of one chunked buffer read
+pub fn buffer_chunks_hook_name(read_id: u32) -> String {
+    format!("buffer_chunks:{}", read_id)
+}
+
+/// Name of the hook that tells one progress entry's owner it was cancelled
+pub fn progress_cancel_hook_name(progress_id: u32) -> String {
+    format!("progress_cancel:{}", progress_id)
+}
+
+/// Name of the hook that runs one `plugin:` save action
+pub fn save_action_hook_name(action: &str) -> String {
+    format!("save_action:{}", action)
+}
+
+/// Name of the hook that runs one file explorer context menu action
+pub fn explorer_action_hook_name(action_id: &str) -> String {
+    format!("explorer_action:{}", action_id)
 }
 
 /// Information about a single line for the LinesChanged hook
@@ -400,7 +627,13 @@ impl Default for HookRegistry {
 
 /// Convert HookArgs to JSON string for plugin communication
 pub fn hook_args_to_json(args: &HookArgs) -> Result<String> {
-    let json_value = match args {
+    serde_json::to_string(&hook_args_to_value(args))
+        .map_err(|e| anyhow!("Failed to serialize hook args: {}", e))
+}
+
+/// Convert HookArgs to the JSON value handed to plugin handlers
+pub fn hook_args_to_value(args: &HookArgs) -> serde_json::Value {
+    match args {
         HookArgs::RenderStart { buffer_id } => {
             serde_json::json!({
                 "buffer_id": buffer_id.0,
@@ -533,6 +766,12 @@ pub fn hook_args_to_json(args: &HookArgs) -> Result<String> {
                 "buffer_id": buffer_id.0,
             })
         }
+        HookArgs::BufferReverted { path, buffer_id } => {
+            serde_json::json!({
+                "path": path.to_string_lossy(),
+                "buffer_id": buffer_id.0,
+            })
+        }
         HookArgs::PreCommand { action } => {
             serde_json::json!({ "action": format!("{:?}", action) })
         }
@@ -675,6 +914,21 @@ pub fn hook_args_to_json(args: &HookArgs) -> Result<String> {
                 "content_y": content_y,
             })
         }
+        HookArgs::MouseMovedInBuffer {
+            buffer_id,
+            split_id,
+            byte_offset,
+            screen_row,
+            screen_col,
+        } => {
+            serde_json::json!({
+                "buffer_id": buffer_id.0,
+                "split_id": split_id.0,
+                "byte_offset": byte_offset,
+                "screen_row": screen_row,
+                "screen_col": screen_col,
+            })
+        }
         HookArgs::LspServerRequest {
             language,
             method,
@@ -703,14 +957,22 @@ pub fn hook_args_to_json(args: &HookArgs) -> Result<String> {
                 "height": height,
             })
         }
+        HookArgs::TerminalResized { width, height } => {
+            serde_json::json!({
+                "width": width,
+                "height": height,
+            })
+        }
         HookArgs::LspServerError {
             language,
+            server,
             server_command,
             error_type,
             message,
         } => {
             serde_json::json!({
                 "language": language,
+                "server": server,
                 "server_command": server_command,
                 "error_type": error_type,
                 "message": message,
@@ -725,6 +987,17 @@ pub fn hook_args_to_json(args: &HookArgs) -> Result<String> {
                 "has_error": has_error,
             })
         }
+        HookArgs::GutterClicked {
+            buffer_id,
+            line,
+            column,
+        } => {
+            serde_json::json!({
+                "buffer_id": buffer_id.0,
+                "line": line,
+                "column": column,
+            })
+        }
         HookArgs::ActionPopupResult {
             popup_id,
             action_id,
@@ -734,9 +1007,148 @@ pub fn hook_args_to_json(args: &HookArgs) -> Result<String> {
                 "action_id": action_id,
             })
         }
-    };
-
-    serde_json::to_string(&json_value).map_err(|e| anyhow!("Failed to serialize hook args: {}", e))
+        HookArgs::PanelRestore {
+            plugin,
+            panel_id,
+            buffer_id,
+            split_id,
+        } => {
+            serde_json::json!({
+                "plugin": plugin,
+                "panel_id": panel_id,
+                "buffer_id": buffer_id.0,
+                "split_id": split_id.map(|id| id.0),
+            })
+        }
+        HookArgs::PathChanged {
+            watch_id,
+            path,
+            kind,
+        } => {
+            serde_json::json!({
+                "watch_id": watch_id,
+                "path": path.to_string_lossy(),
+                "kind": kind.as_str(),
+            })
+        }
+        HookArgs::BufferChunk {
+            read_id,
+            buffer_id,
+            offset,
+            text,
+            done,
+        } => {
+            serde_json::json!({
+                "read_id": read_id,
+                "buffer_id": buffer_id.0,
+                "offset": offset,
+                "text": text,
+                "done": done,
+            })
+        }
+        HookArgs::FileExplorerSelectionChanged { path, is_dir } => {
+            serde_json::json!({
+                "path": path.as_ref().map(|p| p.to_string_lossy()),
+                "is_dir": is_dir,
+            })
+        }
+        HookArgs::ThemeChanged { name } => {
+            serde_json::json!({ "name": name })
+        }
+        HookArgs::ProgressCancelled { progress_id } => {
+            serde_json::json!({ "progress_id": progress_id })
+        }
+        HookArgs::SaveAction {
+            request_id,
+            buffer_id,
+            path,
+            action,
+        } => {
+            serde_json::json!({
+                "request_id": request_id,
+                "buffer_id": buffer_id.0,
+                "path": path.to_string_lossy(),
+                "action": action,
+            })
+        }
+        HookArgs::ExplorerAction {
+            action_id,
+            path,
+            is_dir,
+        } => {
+            serde_json::json!({
+                "action_id": action_id,
+                "path": path.to_string_lossy(),
+                "is_dir": is_dir,
+            })
+        }
+        HookArgs::SplitCreated {
+            split_id,
+            parent_id,
+            direction,
+            by_plugin,
+        } => {
+            serde_json::json!({
+                "split_id": split_id.0,
+                "parent_id": parent_id.0,
+                "direction": direction,
+                "by_plugin": by_plugin,
+            })
+        }
+        HookArgs::SplitClosed {
+            split_id,
+            by_plugin,
+        } => {
+            serde_json::json!({
+                "split_id": split_id.0,
+                "by_plugin": by_plugin,
+            })
+        }
+        HookArgs::SplitFocused {
+            split_id,
+            previous_split_id,
+            by_plugin,
+        } => {
+            serde_json::json!({
+                "split_id": split_id.0,
+                "previous_split_id": previous_split_id.0,
+                "by_plugin": by_plugin,
+            })
+        }
+        HookArgs::SplitResized {
+            split_id,
+            ratio,
+            by_plugin,
+        } => {
+            serde_json::json!({
+                "split_id": split_id.0,
+                "ratio": ratio,
+                "by_plugin": by_plugin,
+            })
+        }
+        HookArgs::LayoutRestored {
+            source,
+            active_split_id,
+        } => {
+            serde_json::json!({
+                "source": source,
+                "active_split_id": active_split_id.0,
+            })
+        }
+        HookArgs::EditRejected {
+            buffer_id,
+            command,
+            reason,
+            rule,
+        } => {
+            serde_json::json!({
+                "buffer_id": buffer_id.0,
+                "command": command,
+                "reason": reason,
+                "rule": rule,
+            })
+        }
+    }
 }
 
 #[cfg(test)]
@@ -846,6 +1258,10 @@ mod tests {
                 buffer_id: BufferId(1),
                 path: PathBuf::from("/test.txt"),
             },
+            HookArgs::BufferReverted {
+                buffer_id: BufferId(1),
+                path: PathBuf::from("/test.txt"),
+            },
             HookArgs::BufferClosed {
                 buffer_id: BufferId(1),
             },
@@ -899,8 +1315,13 @@ mod tests {
             },
             HookArgs::Idle { milliseconds: 500 },
             HookArgs::EditorInitialized,
+            HookArgs::TerminalResized {
+                width: 120,
+                height: 40,
+            },
             HookArgs::LspServerError {
                 language: "python".to_string(),
+                server: None,
                 server_command: "pylsp".to_string(),
                 error_type: "not_found".to_string(),
                 message: "Server not found".to_string(),
@@ -909,10 +1330,89 @@ mod tests {
                 language: "rust".to_string(),
                 has_error: true,
             },
+            HookArgs::GutterClicked {
+                buffer_id: BufferId(1),
+                line: 3,
+                column: "coverage".to_string(),
+            },
+            HookArgs::MouseMovedInBuffer {
+                buffer_id: BufferId(1),
+                split_id: SplitId(1),
+                byte_offset: 42,
+                screen_row: 5,
+                screen_col: 12,
+            },
             HookArgs::ActionPopupResult {
                 popup_id: "test-popup".to_string(),
                 action_id: "copy_pip".to_string(),
             },
+            HookArgs::PanelRestore {
+                plugin: "diagnostics_panel".to_string(),
+                panel_id: "diagnostics".to_string(),
+                buffer_id: BufferId(3),
+                split_id: Some(SplitId(2)),
+            },
+            HookArgs::PathChanged {
+                watch_id: 1,
+                path: PathBuf::from("/tmp/tags"),
+                kind: PathChangeKind::Modified,
+            },
+            HookArgs::BufferChunk {
+                read_id: 1,
+                buffer_id: BufferId(1),
+                offset: 0,
+                text: "fn main() {}".to_string(),
+                done: true,
+            },
+            HookArgs::FileExplorerSelectionChanged {
+                path: Some(PathBuf::from("/project/src")),
+                is_dir: true,
+            },
+            HookArgs::ThemeChanged {
+                name: "dracula".to_string(),
+            },
+            HookArgs::ProgressCancelled { progress_id: 1 },
+            HookArgs::SaveAction {
+                request_id: 1,
+                buffer_id: BufferId(1),
+                path: PathBuf::from("/test.txt"),
+                action: "headers.fix".to_string(),
+            },
+            HookArgs::ExplorerAction {
+                action_id: "open_terminal_here".to_string(),
+                path: PathBuf::from("/project/src"),
+                is_dir: true,
+            },
+            HookArgs::SplitCreated {
+                split_id: SplitId(3),
+                parent_id: SplitId(2),
+                direction: "vertical".to_string(),
+                by_plugin: false,
+            },
+            HookArgs::SplitClosed {
+                split_id: SplitId(3),
+                by_plugin: false,
+            },
+            HookArgs::SplitFocused {
+                split_id: SplitId(0),
+                previous_split_id: SplitId(3),
+                by_plugin: false,
+            },
+            HookArgs::SplitResized {
+                split_id: SplitId(2),
+                ratio: 0.5,
+                by_plugin: false,
+            },
+            HookArgs::LayoutRestored {
+                source: "session".to_string(),
+                active_split_id: SplitId(0),
+            },
+            HookArgs::EditRejected {
+                buffer_id: BufferId(1),
+                command: "insert_text".to_string(),
+                reason: "protected".to_string(),
+                rule: "**/.env*".to_string(),
+            },
         ];
 
         // All should run without panicking
@@ -927,6 +1427,7 @@ mod tests {
         // Test LspServerError serialization
         let args = HookArgs::LspServerError {
             language: "python".to_string(),
+            server: Some("pylsp".to_string()),
             server_command: "pylsp".to_string(),
             error_type: "not_found".to_string(),
             message: "Server 'pylsp' not found in PATH".to_string(),
@@ -934,6 +1435,7 @@ mod tests {
         let json = hook_args_to_json(&args).unwrap();
         let parsed: serde_json::Value = serde_json::from_str(&json).unwrap();
         assert_eq!(parsed["language"], "python");
+        assert_eq!(parsed["server"], "pylsp");
         assert_eq!(parsed["server_command"], "pylsp");
         assert_eq!(parsed["error_type"], "not_found");
         assert!(parsed["message"].as_str().unwrap().contains("pylsp"));
@@ -959,6 +1461,199 @@ mod tests {
         assert_eq!(parsed["action_id"], "copy_pip");
     }
 
+    #[test]
+    fn test_terminal_resized_serialization() {
+        let args = HookArgs::TerminalResized {
+            width: 120,
+            height: 40,
+        };
+        let json = hook_args_to_json(&args).unwrap();
+        let parsed: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed["width"], 120);
+        assert_eq!(parsed["height"], 40);
+    }
+
+    #[test]
+    fn test_panel_restore_serialization() {
+        let args = HookArgs::PanelRestore {
+            plugin: "diagnostics_panel".to_string(),
+            panel_id: "diagnostics".to_string(),
+            buffer_id: BufferId(3),
+            split_id: None,
+        };
+        let json = hook_args_to_json(&args).unwrap();
+        let parsed: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed["plugin"], "diagnostics_panel");
+        assert_eq!(parsed["panel_id"], "diagnostics");
+        assert_eq!(parsed["buffer_id"], 3);
+        assert!(parsed["split_id"].is_null());
+    }
+
+    #[test]
+    fn test_path_changed_serialization() {
+        let args = HookArgs::PathChanged {
+            watch_id: 7,
+            path: PathBuf::from("/project/tags"),
+            kind: PathChangeKind::Created,
+        };
+        let json = hook_args_to_json(&args).unwrap();
+        let parsed: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed["watch_id"], 7);
+        assert_eq!(parsed["path"], "/project/tags");
+        assert_eq!(parsed["kind"], "created");
+        assert_eq!(path_watch_hook_name(7), "path_watch:7");
+    }
+
+    #[test]
+    fn test_buffer_chunk_serialization() {
+        let args = HookArgs::BufferChunk {
+            read_id: 4,
+            buffer_id: BufferId(2),
+            offset: 65536,
+            text: "line \"two\"\n".to_string(),
+            done: false,
+        };
+        let json = hook_args_to_json(&args).unwrap();
+        let parsed: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed["read_id"], 4);
+        assert_eq!(parsed["buffer_id"], 2);
+        assert_eq!(parsed["offset"], 65536);
+        assert_eq!(parsed["text"], "line \"two\"\n");
+        assert_eq!(parsed["done"], false);
+        assert_eq!(buffer_chunks_hook_name(4), "buffer_chunks:4");
+    }
+
+    #[test]
+    fn test_buffer_reverted_serialization() {
+        let args = HookArgs::BufferReverted {
+            buffer_id: BufferId(3),
+            path: PathBuf::from("/project/notes.txt"),
+        };
+        let json = hook_args_to_json(&args).unwrap();
+        let parsed: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed["buffer_id"], 3);
+        assert_eq!(parsed["path"], "/project/notes.txt");
+    }
+
+    #[test]
+    fn test_file_explorer_selection_changed_serialization() {
+        let args = HookArgs::FileExplorerSelectionChanged {
+            path: Some(PathBuf::from("/project/src/main.rs")),
+            is_dir: false,
+        };
+        let json = hook_args_to_json(&args).unwrap();
+        let parsed: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed["path"], "/project/src/main.rs");
+        assert_eq!(parsed["is_dir"], false);
+
+        let args = HookArgs::FileExplorerSelectionChanged {
+            path: None,
+            is_dir: false,
+        };
+        let json = hook_args_to_json(&args).unwrap();
+        let parsed: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert!(parsed["path"].is_null());
+    }
+
+    #[test]
+    fn test_explorer_action_serialization() {
+        let args = HookArgs::ExplorerAction {
+            action_id: "copy_relative".to_string(),
+            path: PathBuf::from("/project/src/main.rs"),
+            is_dir: false,
+        };
+        let json = hook_args_to_json(&args).unwrap();
+        let parsed: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed["action_id"], "copy_relative");
+        assert_eq!(parsed["path"], "/project/src/main.rs");
+        assert_eq!(parsed["is_dir"], false);
+        assert_eq!(
+            explorer_action_hook_name("copy_relative"),
+            "explorer_action:copy_relative"
+        );
+    }
+
+    #[test]
+    fn test_split_hook_args_serialization() {
+        let args = HookArgs::SplitCreated {
+            split_id: SplitId(3),
+            parent_id: SplitId(2),
+            direction: "vertical".to_string(),
+            by_plugin: true,
+        };
+        let json = hook_args_to_json(&args).unwrap();
+        let parsed: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed["split_id"], 3);
+        assert_eq!(parsed["parent_id"], 2);
+        assert_eq!(parsed["direction"], "vertical");
+        assert_eq!(parsed["by_plugin"], true);
+
+        let args = HookArgs::SplitResized {
+            split_id: SplitId(2),
+            ratio: 0.25,
+            by_plugin: false,
+        };
+        let json = hook_args_to_json(&args).unwrap();
+        let parsed: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed["split_id"], 2);
+        assert_eq!(parsed["ratio"], 0.25);
+        assert_eq!(parsed["by_plugin"], false);
+    }
+
+    #[test]
+    fn test_edit_rejected_serialization() {
+        let args = HookArgs::EditRejected {
+            buffer_id: BufferId(4),
+            command: "apply_edits".to_string(),
+            reason: "protected".to_string(),
+            rule: "**/.env*".to_string(),
+        };
+        let json = hook_args_to_json(&args).unwrap();
+        let parsed: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed["buffer_id"], 4);
+        assert_eq!(parsed["command"], "apply_edits");
+        assert_eq!(parsed["reason"], "protected");
+        assert_eq!(parsed["rule"], "**/.env*");
+    }
+
+    #[test]
+    fn test_theme_changed_serialization() {
+        let args = HookArgs::ThemeChanged {
+            name: "dracula".to_string(),
+        };
+        let json = hook_args_to_json(&args).unwrap();
+        let parsed: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed["name"], "dracula");
+    }
+
+    #[test]
+    fn test_progress_cancelled_serialization() {
+        let args = HookArgs::ProgressCancelled { progress_id: 5 };
+        let json = hook_args_to_json(&args).unwrap();
+        let parsed: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed["progress_id"], 5);
+        assert_eq!(progress_cancel_hook_name(5), "progress_cancel:5");
+    }
+
+    #[test]
+    fn test_save_action_serialization() {
+        let args = HookArgs::SaveAction {
+            request_id: 3,
+            buffer_id: BufferId(2),
+            path: PathBuf::from("/src/main.rs"),
+            action: "headers.fix".to_string(),
+        };
+        let json = hook_args_to_json(&args).unwrap();
+        let parsed: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed["request_id"], 3);
+        assert_eq!(parsed["buffer_id"], 2);
+        assert_eq!(parsed["action"], "headers.fix");
+        assert_eq!(
+            save_action_hook_name("headers.fix"),
+            "save_action:headers.fix"
+        );
+    }
+
     #[test]
     fn test_hook_timeout() {
         use std::time::Duration;
@@ -6,12 +6,62 @@
 //! scattered throughout the codebase.
 
 use crate::input::command_registry::CommandRegistry;
-use std::path::Path;
+use std::fmt;
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, RwLock};
 
 #[cfg(feature = "plugins")]
 use super::thread::PluginThreadHandle;
 
+/// Name of the plugin thread. A panic there takes down the plugin runtime,
+/// not the editor.
+pub const PLUGIN_THREAD_NAME: &str = "plugin-runtime";
+
+/// Custom context that is active while the plugin runtime has failed to start
+pub const PLUGIN_RUNTIME_FAILED_CONTEXT: &str = "plugin-runtime-failed";
+
+thread_local! {
+    /// Error the next plugin runtime start on this thread fails with (see
+    /// [`inject_init_error`])
+    static INJECTED_INIT_ERROR: std::cell::RefCell<Option<String>> =
+        const { std::cell::RefCell::new(None) };
+}
+
+/// Make the next plugin runtime start on the calling thread fail with `error`.
+///
+/// Lets tests exercise the degraded no-plugins mode without a broken V8.
+#[doc(hidden)]
+pub fn inject_init_error(error: impl Into<String>) {
+    INJECTED_INIT_ERROR.with(|injected| *injected.borrow_mut() = Some(error.into()));
+}
+
+/// A plugin, or a plugin directory, that failed to load
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PluginLoadError {
+    /// Name of the plugin, None when the directory itself couldn't be read
+    pub plugin: Option<String>,
+    pub message: String,
+}
+
+impl PluginLoadError {
+    /// An error that isn't about a particular plugin
+    pub fn general(message: impl Into<String>) -> Self {
+        Self {
+            plugin: None,
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for PluginLoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.plugin {
+            Some(plugin) => write!(f, "Failed to load plugin '{}': {}", plugin, self.message),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
+
 /// Unified plugin manager that abstracts over the plugin system.
 ///
 /// When the `plugins` feature is enabled, this wraps `PluginThreadHandle`.
@@ -21,47 +71,84 @@ pub struct PluginManager {
     inner: Option<PluginThreadHandle>,
     #[cfg(not(feature = "plugins"))]
     _phantom: std::marker::PhantomData<()>,
+    /// Why the plugin runtime failed to start, if it was requested and did
+    init_error: Option<String>,
 }
 
 impl PluginManager {
     /// Create a new plugin manager.
     ///
     /// When `plugins` feature is enabled and `enable` is true, spawns the plugin thread.
-    /// Otherwise, creates a no-op manager.
-    pub fn new(enable: bool, command_registry: Arc<RwLock<CommandRegistry>>) -> Self {
+    /// Otherwise, creates a no-op manager. If the plugin runtime fails to start
+    /// the manager is a no-op too, and [`Self::init_error`] says why.
+    /// Transpiled plugins are cached in `transpile_cache_dir`.
+    pub fn new(
+        enable: bool,
+        command_registry: Arc<RwLock<CommandRegistry>>,
+        transpile_cache_dir: Option<PathBuf>,
+    ) -> Self {
+        let injected = INJECTED_INIT_ERROR.with(|injected| injected.borrow_mut().take());
+        if let (true, Some(error)) = (enable, injected) {
+            tracing::error!("Failed to start plugin runtime: {}", error);
+            return Self::failed(error);
+        }
+
         #[cfg(feature = "plugins")]
         {
             if enable {
-                match PluginThreadHandle::spawn(command_registry) {
+                match PluginThreadHandle::spawn_with_transpile_cache(
+                    command_registry,
+                    transpile_cache_dir,
+                ) {
                     Ok(handle) => {
                         return Self {
                             inner: Some(handle),
+                            init_error: None,
                         }
                     }
                     Err(e) => {
                         tracing::error!("Failed to spawn TypeScript plugin thread: {}", e);
-                        #[cfg(debug_assertions)]
-                        panic!("TypeScript plugin thread creation failed: {}", e);
+                        return Self::failed(e.to_string());
                     }
                 }
             } else {
                 tracing::info!("Plugins disabled via --no-plugins flag");
             }
-            Self { inner: None }
+            Self {
+                inner: None,
+                init_error: None,
+            }
         }
 
         #[cfg(not(feature = "plugins"))]
         {
-            let _ = command_registry; // Suppress unused warning
+            let _ = (command_registry, transpile_cache_dir); // Suppress unused warning
             if enable {
                 tracing::warn!("Plugins requested but compiled without plugin support");
             }
             Self {
                 _phantom: std::marker::PhantomData,
+                init_error: None,
             }
         }
     }
 
+    /// A no-op manager for a plugin runtime that failed to start
+    fn failed(error: String) -> Self {
+        Self {
+            #[cfg(feature = "plugins")]
+            inner: None,
+            #[cfg(not(feature = "plugins"))]
+            _phantom: std::marker::PhantomData,
+            init_error: Some(error),
+        }
+    }
+
+    /// Why the plugin runtime failed to start (None if it started or wasn't requested)
+    pub fn init_error(&self) -> Option<&str> {
+        self.init_error.as_deref()
+    }
+
     /// Check if the plugin system is active (has a running plugin thread).
     pub fn is_active(&self) -> bool {
         #[cfg(feature = "plugins")]
@@ -75,7 +162,7 @@ impl PluginManager {
     }
 
     /// Load plugins from a directory.
-    pub fn load_plugins_from_dir(&self, dir: &Path) -> Vec<String> {
+    pub fn load_plugins_from_dir(&self, dir: &Path) -> Vec<PluginLoadError> {
         #[cfg(feature = "plugins")]
         {
             if let Some(ref manager) = self.inner {
@@ -90,7 +177,7 @@ impl PluginManager {
         }
     }
 
-    /// Run a hook (fire-and-forget).
+    /// Run a hook (fire-and-forget), queued until the next `dispatch_hooks`.
     pub fn run_hook(&self, hook_name: &str, args: super::hooks::HookArgs) {
         #[cfg(feature = "plugins")]
         {
@@ -104,6 +191,40 @@ impl PluginManager {
         }
     }
 
+    /// Send the hooks queued by `run_hook` to the plugin thread.
+    ///
+    /// Held back while the previous batch runs or its commands are still to
+    /// be drained with `process_commands`. Returns true if any were sent.
+    /// Handlers that set themselves off beyond `limits` are cut off.
+    pub fn dispatch_hooks(&mut self, limits: super::hook_guard::HookLoopLimits) -> bool {
+        #[cfg(feature = "plugins")]
+        {
+            if let Some(ref mut manager) = self.inner {
+                return manager.dispatch_hooks(limits);
+            }
+            false
+        }
+        #[cfg(not(feature = "plugins"))]
+        {
+            let _ = limits;
+            false
+        }
+    }
+
+    /// Check if hooks are queued for the next dispatch.
+    pub fn has_pending_hooks(&self) -> bool {
+        #[cfg(feature = "plugins")]
+        {
+            self.inner
+                .as_ref()
+                .is_some_and(|manager| manager.has_pending_hooks())
+        }
+        #[cfg(not(feature = "plugins"))]
+        {
+            false
+        }
+    }
+
     /// Deliver a response to a pending async plugin operation.
     pub fn deliver_response(&self, response: super::api::PluginResponse) {
         #[cfg(feature = "plugins")]
@@ -119,6 +240,8 @@ impl PluginManager {
     }
 
     /// Process pending plugin commands (non-blocking).
+    ///
+    /// Hooks emitted until `commands_applied` count as caused by them.
     pub fn process_commands(&mut self) -> Vec<super::api::PluginCommand> {
         #[cfg(feature = "plugins")]
         {
@@ -133,6 +256,16 @@ impl PluginManager {
         }
     }
 
+    /// The commands from `process_commands` have been applied.
+    pub fn commands_applied(&self) {
+        #[cfg(feature = "plugins")]
+        {
+            if let Some(ref manager) = self.inner {
+                manager.commands_applied();
+            }
+        }
+    }
+
     /// Get the state snapshot handle for updating editor state.
     #[cfg(feature = "plugins")]
     pub fn state_snapshot_handle(&self) -> Option<Arc<RwLock<super::api::EditorStateSnapshot>>> {
@@ -150,6 +283,18 @@ impl PluginManager {
             .map(|m| m.execute_action_async(action_name))
     }
 
+    /// Execute a plugin action asynchronously, passing it `options`.
+    #[cfg(feature = "plugins")]
+    pub fn execute_action_with_options_async(
+        &self,
+        action_name: &str,
+        options: super::api::ActionOptions,
+    ) -> Option<anyhow::Result<super::thread::oneshot::Receiver<anyhow::Result<()>>>> {
+        self.inner
+            .as_ref()
+            .map(|m| m.execute_action_with_options_async(action_name, options))
+    }
+
     /// List all loaded plugins.
     #[cfg(feature = "plugins")]
     pub fn list_plugins(&self) -> Vec<super::runtime::TsPluginInfo> {
@@ -1,13 +1,14 @@
 //! Plugin system
 //!
 //! The plugin system provides TypeScript/JavaScript plugin support using deno_core.
-//! When the `plugins` feature is disabled, only the type definitions (api, hooks, event_hooks)
+//! When the `plugins` feature is disabled, only the type definitions (api, hooks, event_hooks, hook_guard)
 //! are available - the actual runtime is excluded to avoid deno dependencies.
 //!
 //! Use `PluginManager` as the main interface - it handles both enabled and disabled cases.
 
 pub mod api;
 pub mod event_hooks;
+pub mod hook_guard;
 pub mod hooks;
 pub mod manager;
 
@@ -17,6 +18,8 @@ pub mod process;
 pub mod runtime;
 #[cfg(feature = "plugins")]
 pub mod thread;
+#[cfg(feature = "plugins")]
+pub mod transpile;
 
 #[cfg(feature = "embed-plugins")]
 pub mod embedded;
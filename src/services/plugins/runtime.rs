@@ -39,12 +39,23 @@
 //! a mode (e.g., "normal", "insert", "special"). Custom modes can inherit from parents
 //! and define buffer-local keybindings. Virtual buffers typically use custom modes.
 
+use crate::input::command_args::{CommandArg, CommandArgType};
 use crate::input::commands::Suggestion;
 use crate::model::event::BufferId;
 use crate::model::event::SplitId;
+use crate::primitives::case_matching::CaseMatching;
+use crate::services::file_access::ReadOnlyReason;
+use crate::services::lsp::uri::path_or_uri_to_path;
 use crate::services::plugins::api::{
-    ActionPopupAction, ActionSpec, EditorStateSnapshot, LayoutHints, PluginCommand, ViewTokenWire,
+    ActionOptions, ActionPopupAction, ActionSpec, EditorStateSnapshot, ExplorerActionTarget,
+    LayoutHints, PluginCommand, ViewTokenWire,
 };
+use crate::services::plugins::hooks::{
+    buffer_chunks_hook_name, explorer_action_hook_name, path_watch_hook_name,
+    progress_cancel_hook_name,
+};
+use crate::services::plugins::transpile::Transpiler;
+use crate::view::theme::DecorationColor;
 use anyhow::{anyhow, Result};
 use deno_core::{
     error::ModuleLoaderError, extension, op2, FastString, JsRuntime, ModuleLoadOptions,
@@ -52,15 +63,19 @@ use deno_core::{
     ModuleType, OpState, ResolutionKind, RuntimeOptions,
 };
 use deno_error::JsErrorBox;
+use std::borrow::Cow;
 use std::cell::RefCell;
 use std::collections::HashMap;
+use std::path::PathBuf;
 use std::rc::Rc;
 use std::sync::{Arc, RwLock};
 
 // LayoutHints and ViewTransform are defined in plugin_api
 
 /// Custom module loader that transpiles TypeScript to JavaScript
-struct TypeScriptModuleLoader;
+struct TypeScriptModuleLoader {
+    transpiler: Rc<Transpiler>,
+}
 
 impl deno_core::ModuleLoader for TypeScriptModuleLoader {
     fn resolve(
@@ -79,62 +94,40 @@ impl deno_core::ModuleLoader for TypeScriptModuleLoader {
         _maybe_referrer: Option<&ModuleLoadReferrer>,
         _options: ModuleLoadOptions,
     ) -> ModuleLoadResponse {
-        let specifier = module_specifier.clone();
-        let module_load = async move {
-            let path = specifier
-                .to_file_path()
-                .map_err(|_| JsErrorBox::generic(format!("Invalid file URL: {}", specifier)))?;
-
-            let code = std::fs::read_to_string(&path).map_err(|e| {
-                JsErrorBox::generic(format!("Failed to read {}: {}", path.display(), e))
+        let load = || {
+            let path = module_specifier.to_file_path().map_err(|_| {
+                JsErrorBox::generic(format!("Invalid file URL: {}", module_specifier))
             })?;
 
             // Check if we need to transpile TypeScript
-            let (code, module_type) = if path.extension().and_then(|s| s.to_str()) == Some("ts") {
-                // Transpile TypeScript to JavaScript
-                let transpiled = transpile_typescript(&code, &specifier)?;
-                (transpiled, ModuleType::JavaScript)
+            let code = if path.extension().and_then(|s| s.to_str()) == Some("ts") {
+                match self.transpiler.take_prepared(module_specifier) {
+                    Some(code) => code,
+                    None => self
+                        .transpiler
+                        .transpile_file(module_specifier, &path)
+                        .map_err(|e| JsErrorBox::generic(e.to_string()))?,
+                }
             } else {
-                (code, ModuleType::JavaScript)
+                std::fs::read_to_string(&path).map_err(|e| {
+                    JsErrorBox::generic(format!("Failed to read {}: {}", path.display(), e))
+                })?
             };
 
-            let module_source = ModuleSource::new(
-                module_type,
+            Ok(ModuleSource::new(
+                ModuleType::JavaScript,
                 ModuleSourceCode::String(code.into()),
-                &specifier,
+                module_specifier,
                 None,
-            );
-
-            Ok(module_source)
+            ))
         };
 
-        ModuleLoadResponse::Async(Box::pin(module_load))
+        ModuleLoadResponse::Sync(load())
     }
-}
-
-/// Transpile TypeScript to JavaScript using deno_ast
-fn transpile_typescript(source: &str, specifier: &ModuleSpecifier) -> Result<String, JsErrorBox> {
-    use deno_ast::{EmitOptions, MediaType, ParseParams, TranspileOptions};
 
-    let parsed = deno_ast::parse_module(ParseParams {
-        specifier: specifier.clone(),
-        text: source.into(),
-        media_type: MediaType::TypeScript,
-        capture_tokens: false,
-        scope_analysis: false,
-        maybe_syntax: None,
-    })
-    .map_err(|e| JsErrorBox::generic(format!("TypeScript parse error: {}", e)))?;
-
-    let transpiled = parsed
-        .transpile(
-            &TranspileOptions::default(),
-            &Default::default(),
-            &EmitOptions::default(),
-        )
-        .map_err(|e| JsErrorBox::generic(format!("TypeScript transpile error: {}", e)))?;
-
-    Ok(transpiled.into_source().text.to_string())
+    fn get_source_map(&self, file_name: &str) -> Option<Cow<'_, [u8]>> {
+        self.transpiler.source_map(file_name).map(Cow::Owned)
+    }
 }
 
 /// A cancellable process with pending output collection
@@ -151,8 +144,8 @@ struct TsRuntimeState {
     state_snapshot: Arc<RwLock<EditorStateSnapshot>>,
     /// Command sender for write operations
     command_sender: std::sync::mpsc::Sender<PluginCommand>,
-    /// Event handlers: event_name -> list of global JS function names
-    event_handlers: Rc<RefCell<HashMap<String, Vec<String>>>>,
+    /// Event handlers: event_name -> global JS functions and their plugins
+    event_handlers: Rc<RefCell<HashMap<String, Vec<EventHandlerInfo>>>>,
     /// Pending response senders for async operations (request_id -> sender)
     pending_responses: Arc<
         std::sync::Mutex<
@@ -172,8 +165,42 @@ struct TsRuntimeState {
     process_pids: Rc<RefCell<HashMap<u64, u32>>>,
     /// Next process ID for background processes
     next_process_id: Rc<RefCell<u64>>,
+    /// Path watches: watch_id -> owning plugin
+    path_watches: Rc<RefCell<HashMap<u32, String>>>,
+    /// Next watch ID for path watches
+    next_watch_id: Rc<RefCell<u32>>,
+    /// Progress entries not yet finished: progress_id -> owning plugin
+    progress: Rc<RefCell<HashMap<u32, String>>>,
+    /// Registered gutter columns: column name -> owning plugin
+    gutter_columns: Rc<RefCell<HashMap<String, String>>>,
+    /// File explorer context menu actions: action id -> owning plugin
+    explorer_actions: Rc<RefCell<HashMap<String, String>>>,
+    /// Next read ID for chunked buffer reads
+    next_read_id: Rc<RefCell<u32>>,
+    /// Event being emitted, until the dispatcher takes it
+    pending_event: Option<PendingEvent>,
+}
+
+/// An event and the handlers to call with it
+///
+/// The dispatcher takes it with op_fresh_take_event, so the data is never
+/// spliced into the dispatching script.
+#[derive(serde::Serialize)]
+struct PendingEvent {
+    handlers: Vec<EventHandlerInfo>,
+    data: serde_json::Value,
 }
 
+/// Script calling the handlers of the pending event; its source never
+/// changes, so V8 compiles it once
+const DISPATCH_EVENT_SCRIPT: &str = "__dispatchEvent()";
+
+/// Most path watches a plugin can have at once
+pub const MAX_PATH_WATCHES_PER_PLUGIN: usize = 64;
+
+/// Widest gutter column a plugin can register, in cells
+pub const MAX_GUTTER_COLUMN_WIDTH: u32 = 8;
+
 /// Display a transient message in the editor's status bar
 ///
 /// The message will be shown until the next status update or user action.
@@ -210,9 +237,9 @@ fn op_fresh_apply_theme(state: &mut OpState, #[string] theme_name: String) {
 
 /// Reload configuration from file
 ///
-/// After a plugin saves config changes to the config file, call this to reload
-/// the editor's in-memory configuration. This ensures the editor and plugins
-/// stay in sync with the saved config.
+/// Call this after the config file was changed outside the editor to reload
+/// the editor's in-memory configuration. To change a setting, use
+/// `setConfigValue` instead of writing the config file.
 #[op2(fast)]
 fn op_fresh_reload_config(state: &mut OpState) {
     if let Some(runtime_state) = state.try_borrow::<Rc<RefCell<TsRuntimeState>>>() {
@@ -262,6 +289,53 @@ fn op_fresh_get_user_config(state: &mut OpState) -> serde_json::Value {
     serde_json::Value::Object(serde_json::Map::new())
 }
 
+/// Whether a project-wide search for a pattern should match case
+///
+/// Follows `search.project_smart_case`: when on (the default), only patterns
+/// with an uppercase letter match case; regex escapes such as `\S` don't
+/// count. When off, searches always match case.
+/// @param pattern - The search pattern
+/// @param is_regex - Whether the pattern is a regular expression
+/// @returns true to search case-sensitively
+#[op2(fast)]
+fn op_fresh_is_project_search_case_sensitive(
+    state: &mut OpState,
+    #[string] pattern: String,
+    is_regex: bool,
+) -> bool {
+    let mut smart_case = true;
+    if let Some(runtime_state) = state.try_borrow::<Rc<RefCell<TsRuntimeState>>>() {
+        let runtime_state = runtime_state.borrow();
+        if let Ok(snapshot) = runtime_state.state_snapshot.read() {
+            smart_case = snapshot
+                .config
+                .pointer("/search/project_smart_case")
+                .and_then(serde_json::Value::as_bool)
+                .unwrap_or(true);
+        };
+    }
+    CaseMatching::from_settings(smart_case, true).is_case_sensitive(&pattern, is_regex)
+}
+
+/// Get the colors of the active theme
+///
+/// Keys are the names decorations accept instead of RGB values, such as
+/// "diagnostic.error" or "editor.bg"; colors the theme leaves to the
+/// terminal are missing. Prefer passing names to decorations, which follow
+/// theme changes, over the numbers returned here.
+/// @returns Object mapping color names to [r, g, b]
+#[op2]
+#[serde]
+fn op_fresh_get_theme_colors(state: &mut OpState) -> HashMap<String, (u8, u8, u8)> {
+    if let Some(runtime_state) = state.try_borrow::<Rc<RefCell<TsRuntimeState>>>() {
+        let runtime_state = runtime_state.borrow();
+        if let Ok(snapshot) = runtime_state.state_snapshot.read() {
+            return snapshot.theme_colors.clone();
+        };
+    }
+    HashMap::new()
+}
+
 /// Log an error message from a plugin
 ///
 /// Messages appear in log file when running with RUST_LOG=error.
@@ -460,6 +534,104 @@ fn op_fresh_delete_range(state: &mut OpState, buffer_id: u32, start: u32, end: u
     false
 }
 
+/// Replacement of a byte range of a buffer
+#[derive(serde::Deserialize)]
+struct TsBufferEdit {
+    /// Start byte offset (inclusive)
+    start: usize,
+    /// End byte offset (exclusive)
+    end: usize,
+    /// Text replacing the range
+    text: String,
+}
+
+/// Replace several byte ranges of a buffer at once
+///
+/// Offsets refer to the buffer before any edit applies, so edits don't
+/// shift each other. Ranges must not overlap; if any is invalid none are
+/// applied. All edits are a single undo step, which undo and redo name
+/// `label` in the status bar if given.
+/// Operation is asynchronous; returns true if command was sent successfully.
+/// @param buffer_id - Target buffer ID
+/// @param edits - Ranges to replace and their new text
+/// @param label - Name of the undo step, e.g. "Sort imports"
+/// @example
+/// // Remove a trailing semicolon on the first two lines
+/// editor.applyEdits(bufferId, [{ start: 9, end: 10, text: "" }, { start: 20, end: 21, text: "" }], "Remove semicolons");
+#[op2]
+fn op_fresh_apply_edits(
+    state: &mut OpState,
+    buffer_id: u32,
+    #[serde] edits: Vec<TsBufferEdit>,
+    #[string] label: Option<String>,
+) -> bool {
+    if let Some(runtime_state) = state.try_borrow::<Rc<RefCell<TsRuntimeState>>>() {
+        let runtime_state = runtime_state.borrow();
+        let edits = edits
+            .into_iter()
+            .map(|edit| (edit.start..edit.end, edit.text))
+            .collect();
+        let result = runtime_state
+            .command_sender
+            .send(PluginCommand::ApplyEdits {
+                buffer_id: BufferId(buffer_id as usize),
+                edits,
+                label,
+            });
+        return result.is_ok();
+    }
+    false
+}
+
+/// Finish a save action started by a "save_action:<name>" event
+///
+/// Call this once the handler's edits are sent; the next save action then
+/// runs. Passing an error reports the step as failed.
+/// @param request_id - The request_id of the save_action event
+/// @param error - Why the step failed, or null if it succeeded
+/// @example
+/// globalThis.addLicense = (args) => {
+///   editor.insertText(args.buffer_id, 0, "// SPDX-License-Identifier: MIT\n");
+///   editor.finishSaveAction(args.request_id, null);
+/// };
+/// editor.on("save_action:license", "addLicense");
+#[op2]
+fn op_fresh_finish_save_action(
+    state: &mut OpState,
+    request_id: u32,
+    #[string] error: Option<String>,
+) -> bool {
+    if let Some(runtime_state) = state.try_borrow::<Rc<RefCell<TsRuntimeState>>>() {
+        let runtime_state = runtime_state.borrow();
+        let result = runtime_state
+            .command_sender
+            .send(PluginCommand::FinishSaveAction { request_id, error });
+        return result.is_ok();
+    }
+    false
+}
+
+/// Color of a decoration: the theme color `name`, or `rgb` if it is empty
+fn decoration_color(name: String, (r, g, b): (u8, u8, u8)) -> DecorationColor {
+    if name.is_empty() {
+        DecorationColor::Rgb(r, g, b)
+    } else {
+        DecorationColor::Named(name)
+    }
+}
+
+/// Optional color of a decoration, such as its background: the theme color
+/// `name`, or `rgb` unless a component is negative
+fn decoration_bg_color(name: String, (r, g, b): (i16, i16, i16)) -> Option<DecorationColor> {
+    if !name.is_empty() {
+        Some(DecorationColor::Named(name))
+    } else if r >= 0 && g >= 0 && b >= 0 {
+        Some(DecorationColor::Rgb(r as u8, g as u8, b as u8))
+    } else {
+        None
+    }
+}
+
 /// Add a colored highlight overlay to text without modifying content
 ///
 /// Overlays are visual decorations that persist until explicitly removed.
@@ -477,6 +649,8 @@ fn op_fresh_delete_range(state: &mut OpState, buffer_id: u32, start: u32, end: u
 /// @param bold - Use bold text
 /// @param italic - Use italic text
 /// @param extend_to_line_end - Extend background to end of visual line
+/// @param color_name - Theme color used instead of r, g, b (e.g. "diagnostic.error"), empty for none
+/// @param bg_color_name - Theme color used instead of bg_r, bg_g, bg_b, empty for none
 /// @returns true if overlay was added
 #[op2(fast)]
 #[allow(clippy::too_many_arguments)]
@@ -496,6 +670,8 @@ fn op_fresh_add_overlay(
     bold: bool,
     italic: bool,
     extend_to_line_end: bool,
+    #[string] color_name: String,
+    #[string] bg_color_name: String,
 ) -> bool {
     if let Some(runtime_state) = state.try_borrow::<Rc<RefCell<TsRuntimeState>>>() {
         let runtime_state = runtime_state.borrow();
@@ -507,20 +683,14 @@ fn op_fresh_add_overlay(
             ))
         };
 
-        let bg_color = if bg_r >= 0 && bg_g >= 0 && bg_b >= 0 {
-            Some((bg_r as u8, bg_g as u8, bg_b as u8))
-        } else {
-            None
-        };
-
         let result = runtime_state
             .command_sender
             .send(PluginCommand::AddOverlay {
                 buffer_id: BufferId(buffer_id as usize),
                 namespace: ns,
                 range: (start as usize)..(end as usize),
-                color: (r, g, b),
-                bg_color,
+                color: decoration_color(color_name, (r, g, b)),
+                bg_color: decoration_bg_color(bg_color_name, (bg_r, bg_g, bg_b)),
                 underline,
                 bold,
                 italic,
@@ -573,6 +743,32 @@ fn op_fresh_clear_namespace(
     false
 }
 
+/// Keep a namespace's overlays when a buffer is reverted
+///
+/// "Revert Buffer to Saved" clears the overlays of every namespace not marked
+/// stable, since their positions refer to the replaced text.
+/// @param namespace - The overlay namespace
+/// @param stable - true to keep its overlays across reverts, false to clear them
+/// @returns true if the command was sent
+#[op2(fast)]
+fn op_fresh_set_revert_stable_namespace(
+    state: &mut OpState,
+    #[string] namespace: String,
+    stable: bool,
+) -> bool {
+    if let Some(runtime_state) = state.try_borrow::<Rc<RefCell<TsRuntimeState>>>() {
+        let runtime_state = runtime_state.borrow();
+        let result = runtime_state
+            .command_sender
+            .send(PluginCommand::SetRevertStableNamespace {
+                namespace: crate::view::overlay::OverlayNamespace::from_string(namespace),
+                stable,
+            });
+        return result.is_ok();
+    }
+    false
+}
+
 /// Clear all overlays that overlap with a byte range
 /// @param buffer_id - The buffer ID
 /// @param start - Start byte position (inclusive)
@@ -618,6 +814,47 @@ fn op_fresh_set_line_numbers(state: &mut OpState, buffer_id: u32, enabled: bool)
     false
 }
 
+/// Let the editor close a buffer created by this plugin once no split has
+/// shown it and no plugin has used it for `editor.plugin_buffer_idle_close_secs`
+/// (the default for such buffers), or keep it while hidden
+/// @param buffer_id - The buffer ID
+/// @param enabled - false to keep a buffer that is hidden on purpose
+/// @returns true if the command was sent
+#[op2(fast)]
+fn op_fresh_set_buffer_idle_close(state: &mut OpState, buffer_id: u32, enabled: bool) -> bool {
+    if let Some(runtime_state) = state.try_borrow::<Rc<RefCell<TsRuntimeState>>>() {
+        let runtime_state = runtime_state.borrow();
+        let result = runtime_state
+            .command_sender
+            .send(PluginCommand::SetBufferIdleClose {
+                buffer_id: BufferId(buffer_id as usize),
+                enabled,
+            });
+        return result.is_ok();
+    }
+    false
+}
+
+/// Pin a buffer so bulk close commands and automatic closing leave it open,
+/// or unpin it
+/// @param buffer_id - The buffer ID
+/// @param pinned - Whether the buffer is pinned
+/// @returns true if the command was sent
+#[op2(fast)]
+fn op_fresh_set_buffer_pinned(state: &mut OpState, buffer_id: u32, pinned: bool) -> bool {
+    if let Some(runtime_state) = state.try_borrow::<Rc<RefCell<TsRuntimeState>>>() {
+        let runtime_state = runtime_state.borrow();
+        let result = runtime_state
+            .command_sender
+            .send(PluginCommand::SetBufferPinned {
+                buffer_id: BufferId(buffer_id as usize),
+                pinned,
+            });
+        return result.is_ok();
+    }
+    false
+}
+
 /// Remove all overlays from a buffer
 /// @param buffer_id - The buffer ID
 /// @returns true if overlays were cleared
@@ -645,6 +882,7 @@ fn op_fresh_clear_all_overlays(state: &mut OpState, buffer_id: u32) -> bool {
 /// @param b - Blue color component (0-255)
 /// @param before - Whether to insert before (true) or after (false) the position
 /// @param use_bg - Whether to use the color as background (true) or foreground (false)
+/// @param color_name - Theme color used instead of r, g, b, empty for none
 /// @returns true if virtual text was added
 #[op2(fast)]
 #[allow(clippy::too_many_arguments)]
@@ -659,6 +897,7 @@ fn op_fresh_add_virtual_text(
     b: u8,
     before: bool,
     use_bg: bool,
+    #[string] color_name: String,
 ) -> bool {
     if let Some(runtime_state) = state.try_borrow::<Rc<RefCell<TsRuntimeState>>>() {
         let runtime_state = runtime_state.borrow();
@@ -669,7 +908,7 @@ fn op_fresh_add_virtual_text(
                 virtual_text_id,
                 position: position as usize,
                 text,
-                color: (r, g, b),
+                color: decoration_color(color_name, (r, g, b)),
                 use_bg,
                 before,
             });
@@ -754,6 +993,8 @@ fn op_fresh_clear_virtual_texts(state: &mut OpState, buffer_id: u32) -> bool {
 /// @param above - Whether to insert above (true) or below (false) the line
 /// @param namespace - Namespace for bulk removal (e.g., "git-blame")
 /// @param priority - Priority for ordering multiple lines at same position
+/// @param fg_color_name - Theme color used instead of fg_r, fg_g, fg_b, empty for none
+/// @param bg_color_name - Theme color used instead of bg_r, bg_g, bg_b, empty for none
 /// @returns true if virtual line was added
 #[op2(fast)]
 #[allow(clippy::too_many_arguments)]
@@ -771,25 +1012,20 @@ fn op_fresh_add_virtual_line(
     above: bool,
     #[string] namespace: String,
     priority: i32,
+    #[string] fg_color_name: String,
+    #[string] bg_color_name: String,
 ) -> bool {
     if let Some(runtime_state) = state.try_borrow::<Rc<RefCell<TsRuntimeState>>>() {
         let runtime_state = runtime_state.borrow();
-
-        // Convert background color: -1 means transparent (None)
-        let bg_color = if bg_r >= 0 && bg_g >= 0 && bg_b >= 0 {
-            Some((bg_r as u8, bg_g as u8, bg_b as u8))
-        } else {
-            None
-        };
-
         let result = runtime_state
             .command_sender
             .send(PluginCommand::AddVirtualLine {
                 buffer_id: BufferId(buffer_id as usize),
                 position: position as usize,
                 text,
-                fg_color: (fg_r, fg_g, fg_b),
-                bg_color,
+                fg_color: decoration_color(fg_color_name, (fg_r, fg_g, fg_b)),
+                // -1 components mean transparent
+                bg_color: decoration_bg_color(bg_color_name, (bg_r, bg_g, bg_b)),
                 above,
                 namespace,
                 priority,
@@ -839,7 +1075,8 @@ fn op_fresh_refresh_lines(state: &mut OpState, buffer_id: u32) -> bool {
     false
 }
 
-/// Set a line indicator in the gutter's indicator column
+/// Set a line indicator in the gutter's indicator column, or in a column
+/// added with registerGutterColumn
 /// @param buffer_id - The buffer ID
 /// @param line - Line number (0-indexed)
 /// @param namespace - Namespace for grouping (e.g., "git-gutter", "breakpoints")
@@ -848,6 +1085,8 @@ fn op_fresh_refresh_lines(state: &mut OpState, buffer_id: u32) -> bool {
 /// @param g - Green color component (0-255)
 /// @param b - Blue color component (0-255)
 /// @param priority - Priority for display when multiple indicators exist (higher wins)
+/// @param color_name - Theme color used instead of r, g, b (e.g. "diff.added"), empty for none
+/// @param column - Name of a registered gutter column, empty for the indicator column
 /// @returns true if indicator was set
 #[op2(fast)]
 #[allow(clippy::too_many_arguments)]
@@ -861,6 +1100,8 @@ fn op_fresh_set_line_indicator(
     g: u8,
     b: u8,
     priority: i32,
+    #[string] color_name: String,
+    #[string] column: String,
 ) -> bool {
     if let Some(runtime_state) = state.try_borrow::<Rc<RefCell<TsRuntimeState>>>() {
         let runtime_state = runtime_state.borrow();
@@ -871,8 +1112,9 @@ fn op_fresh_set_line_indicator(
                 line: line as usize,
                 namespace,
                 symbol,
-                color: (r, g, b),
+                color: decoration_color(color_name, (r, g, b)),
                 priority,
+                column: (!column.is_empty()).then_some(column),
             });
         return result.is_ok();
     }
@@ -902,6 +1144,192 @@ fn op_fresh_clear_line_indicators(
     false
 }
 
+/// Add a named column to the gutter of every buffer
+///
+/// The column shows the line indicators set for it through setLineIndicator's
+/// `column` argument, so they don't compete with other plugins' indicators
+/// for the one-cell indicator column. Users can move, resize or hide it with
+/// the `editor.gutter_columns` and `editor.gutter_column_widths` settings.
+/// The column goes away when the plugin is unloaded.
+/// Throws if another plugin owns the name or the width or alignment is invalid.
+/// @param name - Column name, e.g. "coverage"
+/// @param width - Width in cells (1-8)
+/// @param alignment - "left", "center" or "right"
+/// @returns true if the column was added
+/// @example
+/// editor.registerGutterColumn("coverage", 2, "left");
+/// editor.setLineIndicator(bufferId, 9, "coverage", "▌", 0, 0, 0, 0, "diff.added", "coverage");
+#[op2(fast)]
+fn op_fresh_register_gutter_column(
+    state: &mut OpState,
+    #[string] name: String,
+    width: u32,
+    #[string] alignment: String,
+    #[string] plugin_name: String,
+) -> Result<bool, JsErrorBox> {
+    use crate::view::margin::{GutterAlignment, GutterColumn};
+
+    let Some(runtime_state) = state.try_borrow::<Rc<RefCell<TsRuntimeState>>>() else {
+        return Err(JsErrorBox::generic("Plugin runtime is not available"));
+    };
+    let runtime_state = runtime_state.borrow();
+
+    if name.is_empty() || name == GutterColumn::INDICATORS || name == GutterColumn::LINE_NUMBERS {
+        return Err(JsErrorBox::generic(format!(
+            "Cannot register gutter column '{}': the name is reserved",
+            name
+        )));
+    }
+    if !(1..=MAX_GUTTER_COLUMN_WIDTH).contains(&width) {
+        return Err(JsErrorBox::generic(format!(
+            "Cannot register gutter column '{}': width must be 1 to {}",
+            name, MAX_GUTTER_COLUMN_WIDTH
+        )));
+    }
+    let Some(alignment) = GutterAlignment::from_name(&alignment) else {
+        return Err(JsErrorBox::generic(format!(
+            "Cannot register gutter column '{}': alignment must be \"left\", \"center\" or \"right\"",
+            name
+        )));
+    };
+
+    let mut columns = runtime_state.gutter_columns.borrow_mut();
+    if let Some(owner) = columns.get(&name).filter(|owner| **owner != plugin_name) {
+        return Err(JsErrorBox::generic(format!(
+            "Cannot register gutter column '{}': plugin '{}' already registered it",
+            name, owner
+        )));
+    }
+    columns.insert(name.clone(), plugin_name);
+
+    let result = runtime_state
+        .command_sender
+        .send(PluginCommand::RegisterGutterColumn {
+            name,
+            width: width as usize,
+            alignment,
+        });
+    Ok(result.is_ok())
+}
+
+/// Remove a gutter column added with registerGutterColumn
+/// @param name - Column name
+/// @returns true if the plugin had registered the column
+#[op2(fast)]
+fn op_fresh_unregister_gutter_column(
+    state: &mut OpState,
+    #[string] name: String,
+    #[string] plugin_name: String,
+) -> bool {
+    if let Some(runtime_state) = state.try_borrow::<Rc<RefCell<TsRuntimeState>>>() {
+        let runtime_state = runtime_state.borrow();
+        let owned = runtime_state.gutter_columns.borrow().get(&name) == Some(&plugin_name);
+        return owned && runtime_state.remove_gutter_column(&name);
+    }
+    false
+}
+
+/// Options for registerExplorerAction
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct TsExplorerActionOptions {
+    /// Id of the action, e.g. "open_terminal_here"
+    pub id: String,
+    /// Text of the menu entry
+    pub label: String,
+    /// Entries it is offered on: "file", "dir" or "any" (default "any")
+    pub when: Option<String>,
+    /// Name of the globalThis function called with `{action_id, path, is_dir}`
+    pub handler: String,
+}
+
+/// Add an entry to the file explorer's context menu
+///
+/// Plugin entries show below the built-in ones, in registration order, on the
+/// entries `when` names. Choosing one calls the handler with the path of the
+/// file or directory the menu was opened on. Registering the same id again
+/// changes the entry. The entry goes away when the plugin is unloaded.
+/// Throws if another plugin owns the id or `when` is invalid.
+/// @param options - Id, label, entries it is offered on and handler name
+/// @returns true if the entry was added
+/// @example
+/// globalThis.onTerminalHere = (target) => {
+///   editor.setStatus(`terminal in ${target.path}`);
+/// };
+/// editor.registerExplorerAction({ id: "terminal_here", label: "Open Terminal Here", when: "dir", handler: "onTerminalHere" });
+#[op2]
+fn op_fresh_register_explorer_action(
+    state: &mut OpState,
+    #[serde] options: TsExplorerActionOptions,
+    #[string] plugin_name: String,
+) -> Result<bool, JsErrorBox> {
+    let Some(runtime_state) = state.try_borrow::<Rc<RefCell<TsRuntimeState>>>() else {
+        return Err(JsErrorBox::generic("Plugin runtime is not available"));
+    };
+    let runtime_state = runtime_state.borrow();
+
+    let TsExplorerActionOptions {
+        id,
+        label,
+        when,
+        handler,
+    } = options;
+    if id.is_empty() {
+        return Err(JsErrorBox::generic(
+            "Cannot register explorer action: the id is empty",
+        ));
+    }
+    let when = when.as_deref().unwrap_or("any");
+    let Some(when) = ExplorerActionTarget::from_name(when) else {
+        return Err(JsErrorBox::generic(format!(
+            "Cannot register explorer action '{}': when must be \"file\", \"dir\" or \"any\"",
+            id
+        )));
+    };
+
+    let mut actions = runtime_state.explorer_actions.borrow_mut();
+    if let Some(owner) = actions.get(&id).filter(|owner| **owner != plugin_name) {
+        return Err(JsErrorBox::generic(format!(
+            "Cannot register explorer action '{}': plugin '{}' already registered it",
+            id, owner
+        )));
+    }
+    actions.insert(id.clone(), plugin_name.clone());
+    runtime_state.event_handlers.borrow_mut().insert(
+        explorer_action_hook_name(&id),
+        vec![EventHandlerInfo {
+            handler,
+            plugin: plugin_name.clone(),
+        }],
+    );
+
+    let result = runtime_state
+        .command_sender
+        .send(PluginCommand::RegisterExplorerAction {
+            id,
+            label,
+            when,
+            plugin: plugin_name,
+        });
+    Ok(result.is_ok())
+}
+
+/// Remove an entry added with registerExplorerAction
+/// @param id - Id of the action
+/// @returns true if the plugin had registered the action
+#[op2(fast)]
+fn op_fresh_unregister_explorer_action(
+    state: &mut OpState,
+    #[string] id: String,
+    #[string] plugin_name: String,
+) -> bool {
+    if let Some(runtime_state) = state.try_borrow::<Rc<RefCell<TsRuntimeState>>>() {
+        let runtime_state = runtime_state.borrow();
+        let owned = runtime_state.explorer_actions.borrow().get(&id) == Some(&plugin_name);
+        return owned && runtime_state.remove_explorer_action(&id);
+    }
+    false
+}
+
 /// Submit a transformed view stream for a viewport
 /// @param buffer_id - Buffer to apply the transform to
 /// @param start - Viewport start byte
@@ -1008,8 +1436,40 @@ fn op_fresh_plugin_translate(
     crate::i18n::translate_plugin_string(&plugin_name, &key, &args)
 }
 
+/// A parameter of a registered command
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct TsCommandArg {
+    /// Key of the value in the action's `options.args`
+    pub name: String,
+    /// "string", "number", "path" or "choice"
+    pub r#type: String,
+    /// Values offered for a "choice" parameter
+    pub choices: Option<Vec<String>>,
+    /// Whether the parameter may be left out (default false)
+    pub optional: Option<bool>,
+}
+
+/// Options for registerCommand
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+pub struct TsCommandOptions {
+    /// The action honors `options.dry_run` by reporting what it would do
+    /// instead of doing it
+    pub dry_run: Option<bool>,
+    /// Parameters collected before the action runs, passed by name in
+    /// `options.args`
+    pub args: Option<Vec<TsCommandArg>>,
+}
+
 /// Register a custom command that can be triggered by keybindings or the command palette
-#[op2(fast)]
+/// Plugins pass `{ dry_run: true }` after the contexts when the action honors
+/// `options.dry_run` by reporting what it would do instead of doing it.
+/// Declared `args` are prompted for when the command is run from the
+/// palette, and checked when given by a `run_command` key binding or as
+/// `:Name value…` in the palette. Throws if a parameter type is invalid.
+/// @example
+/// globalThis.greet = (options) => editor.setStatus(`Hello ${options.args.who}`);
+/// editor.registerCommand("Greet", "Greet someone", "greet", "", { args: [{ name: "who", type: "string" }] });
+#[op2]
 fn op_fresh_register_command(
     state: &mut OpState,
     #[string] name: String,
@@ -1017,7 +1477,25 @@ fn op_fresh_register_command(
     #[string] action: String,
     #[string] contexts: String,
     #[string] source: String,
-) -> bool {
+    #[serde] options: TsCommandOptions,
+) -> Result<bool, JsErrorBox> {
+    let mut args = Vec::new();
+    for arg in options.args.unwrap_or_default() {
+        let Some(arg_type) =
+            CommandArgType::from_name(&arg.r#type, arg.choices.unwrap_or_default())
+        else {
+            return Err(JsErrorBox::generic(format!(
+                "Cannot register command '{}': parameter '{}' must have type \"string\", \"number\", \"path\" or \"choice\" with choices",
+                name, arg.name
+            )));
+        };
+        args.push(CommandArg {
+            name: arg.name,
+            arg_type,
+            optional: arg.optional.unwrap_or(false),
+        });
+    }
+
     if let Some(runtime_state) = state.try_borrow::<Rc<RefCell<TsRuntimeState>>>() {
         let runtime_state = runtime_state.borrow();
 
@@ -1066,10 +1544,14 @@ fn op_fresh_register_command(
 
         let result = runtime_state
             .command_sender
-            .send(PluginCommand::RegisterCommand { command });
-        return result.is_ok();
+            .send(PluginCommand::RegisterCommand {
+                command,
+                dry_run: options.dry_run.unwrap_or(false),
+                args,
+            });
+        return Ok(result.is_ok());
     }
-    false
+    Ok(false)
 }
 
 /// Unregister a custom command by name
@@ -1106,18 +1588,28 @@ fn op_fresh_set_context(state: &mut OpState, #[string] name: String, active: boo
 }
 
 /// Open a file in the editor, optionally at a specific location
-/// @param path - File path to open
+///
+/// `file://` URIs (as returned by language servers) are accepted and
+/// percent-decoded. Throws for other URIs, such as `untitled:` or `https:`,
+/// which don't name a local file.
+/// @param path - File path or file:// URI to open
 /// @param line - Line number to jump to (0 for no jump)
 /// @param column - Column number to jump to (0 for no jump)
 /// @returns true if file was opened
-#[op2(fast)]
-fn op_fresh_open_file(state: &mut OpState, #[string] path: String, line: u32, column: u32) -> bool {
+#[op2]
+fn op_fresh_open_file(
+    state: &mut OpState,
+    #[string] path: String,
+    line: u32,
+    column: u32,
+) -> Result<bool, JsErrorBox> {
+    let path = path_or_uri_to_path(&path).map_err(|e| JsErrorBox::generic(e.to_string()))?;
     if let Some(runtime_state) = state.try_borrow::<Rc<RefCell<TsRuntimeState>>>() {
         let runtime_state = runtime_state.borrow();
         let result = runtime_state
             .command_sender
             .send(PluginCommand::OpenFileAtLocation {
-                path: std::path::PathBuf::from(path),
+                path,
                 line: if line == 0 { None } else { Some(line as usize) },
                 column: if column == 0 {
                     None
@@ -1125,9 +1617,9 @@ fn op_fresh_open_file(state: &mut OpState, #[string] path: String, line: u32, co
                     Some(column as usize)
                 },
             });
-        return result.is_ok();
+        return Ok(result.is_ok());
     }
-    false
+    Ok(false)
 }
 
 /// Get the ID of the focused split pane
@@ -1147,19 +1639,16 @@ fn op_fresh_get_active_split_id(state: &mut OpState) -> u32 {
 
 /// Get the line number of the primary cursor (1-indexed)
 ///
-/// Line numbers start at 1. Returns 1 if no cursor exists.
+/// Line numbers start at 1. Returns 1 if no cursor exists. In buffers too
+/// large to have a line index the number is estimated.
 /// For byte offset use getCursorPosition instead.
 #[op2(fast)]
 fn op_fresh_get_cursor_line(state: &mut OpState) -> u32 {
     if let Some(runtime_state) = state.try_borrow::<Rc<RefCell<TsRuntimeState>>>() {
         let runtime_state = runtime_state.borrow();
         if let Ok(snapshot) = runtime_state.state_snapshot.read() {
-            if let Some(cursor) = &snapshot.primary_cursor {
-                // Simplified: estimate line number from byte position
-                // In a real implementation, this would use buffer content
-                // For now, return 1 as placeholder
-                let _ = cursor.position;
-                return 1;
+            if let Some(line) = snapshot.primary_cursor_line {
+                return line as u32 + 1;
             }
         };
     }
@@ -1187,26 +1676,29 @@ fn op_fresh_get_all_cursor_positions(state: &mut OpState) -> Vec<u32> {
 }
 
 /// Open a file in a specific split pane
+///
+/// Accepts `file://` URIs and throws for other URIs, like openFile.
 /// @param split_id - The split ID to open the file in
-/// @param path - File path to open
+/// @param path - File path or file:// URI to open
 /// @param line - Line number to jump to (0 for no jump)
 /// @param column - Column number to jump to (0 for no jump)
 /// @returns true if file was opened
-#[op2(fast)]
+#[op2]
 fn op_fresh_open_file_in_split(
     state: &mut OpState,
     split_id: u32,
     #[string] path: String,
     line: u32,
     column: u32,
-) -> bool {
+) -> Result<bool, JsErrorBox> {
+    let path = path_or_uri_to_path(&path).map_err(|e| JsErrorBox::generic(e.to_string()))?;
     if let Some(runtime_state) = state.try_borrow::<Rc<RefCell<TsRuntimeState>>>() {
         let runtime_state = runtime_state.borrow();
         let result = runtime_state
             .command_sender
             .send(PluginCommand::OpenFileInSplit {
                 split_id: split_id as usize,
-                path: std::path::PathBuf::from(path),
+                path,
                 line: if line == 0 { None } else { Some(line as usize) },
                 column: if column == 0 {
                     None
@@ -1214,9 +1706,9 @@ fn op_fresh_open_file_in_split(
                     Some(column as usize)
                 },
             });
-        return result.is_ok();
+        return Ok(result.is_ok());
     }
-    false
+    Ok(false)
 }
 
 /// Result from spawnProcess
@@ -1634,7 +2126,9 @@ async fn op_fresh_delay(#[bigint] ms: u64) -> Result<(), JsErrorBox> {
 /// Subscribe to an editor event
 ///
 /// Handler must be a global function name (not a closure).
-/// Multiple handlers can be registered for the same event.
+/// Multiple handlers can be registered for the same event; registering the
+/// same handler twice has no further effect. Handlers are removed when their
+/// plugin is unloaded or reloaded.
 /// Events: "buffer_save", "cursor_moved", "buffer_modified", etc.
 /// @param event_name - Event to subscribe to
 /// @param handler_name - Name of globalThis function to call with event data
@@ -1648,19 +2142,25 @@ fn op_fresh_on(
     state: &mut OpState,
     #[string] event_name: String,
     #[string] handler_name: String,
+    #[string] plugin_name: String,
 ) -> bool {
     if let Some(runtime_state) = state.try_borrow::<Rc<RefCell<TsRuntimeState>>>() {
         let runtime_state = runtime_state.borrow();
         let mut handlers = runtime_state.event_handlers.borrow_mut();
-        handlers
-            .entry(event_name.clone())
-            .or_insert_with(Vec::new)
-            .push(handler_name.clone());
-        tracing::debug!(
-            "Registered event handler '{}' for '{}'",
-            handler_name,
-            event_name
-        );
+        let handler_list = handlers.entry(event_name.clone()).or_default();
+        let handler = EventHandlerInfo {
+            handler: handler_name,
+            plugin: plugin_name,
+        };
+        if !handler_list.contains(&handler) {
+            tracing::debug!(
+                "Registered event handler '{}' of plugin '{}' for '{}'",
+                handler.handler,
+                handler.plugin,
+                event_name
+            );
+            handler_list.push(handler);
+        }
         return true;
     }
     false
@@ -1675,12 +2175,16 @@ fn op_fresh_off(
     state: &mut OpState,
     #[string] event_name: String,
     #[string] handler_name: String,
+    #[string] plugin_name: String,
 ) -> bool {
     if let Some(runtime_state) = state.try_borrow::<Rc<RefCell<TsRuntimeState>>>() {
         let runtime_state = runtime_state.borrow();
         let mut handlers = runtime_state.event_handlers.borrow_mut();
         if let Some(handler_list) = handlers.get_mut(&event_name) {
-            if let Some(pos) = handler_list.iter().position(|h| h == &handler_name) {
+            if let Some(pos) = handler_list
+                .iter()
+                .position(|h| h.handler == handler_name && h.plugin == plugin_name)
+            {
                 handler_list.remove(pos);
                 tracing::debug!(
                     "Unregistered event handler '{}' from '{}'",
@@ -1694,12 +2198,26 @@ fn op_fresh_off(
     false
 }
 
+/// Take the event being emitted (internal, used by the event dispatcher)
+#[op2]
+#[serde]
+fn op_fresh_take_event(state: &mut OpState) -> Option<PendingEvent> {
+    state
+        .try_borrow::<Rc<RefCell<TsRuntimeState>>>()?
+        .borrow_mut()
+        .pending_event
+        .take()
+}
+
 /// Get list of registered handlers for an event
 /// @param event_name - Name of the event
-/// @returns Array of handler function names
+/// @returns Array of handlers with the plugin that registered each
 #[op2]
 #[serde]
-fn op_fresh_get_handlers(state: &mut OpState, #[string] event_name: String) -> Vec<String> {
+fn op_fresh_get_handlers(
+    state: &mut OpState,
+    #[string] event_name: String,
+) -> Vec<EventHandlerInfo> {
     if let Some(runtime_state) = state.try_borrow::<Rc<RefCell<TsRuntimeState>>>() {
         let runtime_state = runtime_state.borrow();
         let handlers = runtime_state.event_handlers.borrow();
@@ -1710,6 +2228,15 @@ fn op_fresh_get_handlers(state: &mut OpState, #[string] event_name: String) -> V
     Vec::new()
 }
 
+/// A registered event handler
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct EventHandlerInfo {
+    /// Name of the globalThis function called with the event data
+    pub handler: String,
+    /// Plugin that registered the handler
+    pub plugin: String,
+}
+
 /// File stat information
 #[derive(serde::Serialize)]
 struct FileStat {
@@ -1736,6 +2263,12 @@ struct TsBufferInfo {
     modified: bool,
     /// Buffer length in bytes
     length: u32,
+    /// Whether the buffer's file can be written
+    writable: bool,
+    /// Why the file can't be written ("permissions" or "read_only_filesystem"), null if it can
+    read_only_reason: Option<String>,
+    /// Whether the buffer is pinned, so bulk closes leave it open
+    pinned: bool,
 }
 
 /// Diff vs last save for a buffer
@@ -1857,6 +2390,38 @@ struct TsDiagnostic {
     range: TsDiagnosticRange,
 }
 
+/// Diagnostic counts of one file
+#[derive(serde::Serialize)]
+struct TsFileDiagnosticCounts {
+    /// Canonical file URI, the same for every spelling a server used
+    uri: String,
+    /// Local path of the file (null for URIs that don't name a local file)
+    path: Option<String>,
+    /// Number of errors
+    errors: u32,
+    /// Number of warnings
+    warnings: u32,
+    /// Number of information diagnostics
+    infos: u32,
+    /// Number of hints, including diagnostics without a severity
+    hints: u32,
+}
+
+/// Diagnostic counts of the workspace and of each file
+#[derive(serde::Serialize)]
+struct TsDiagnosticsSummary {
+    /// Number of errors in all files
+    errors: u32,
+    /// Number of warnings in all files
+    warnings: u32,
+    /// Number of information diagnostics in all files
+    infos: u32,
+    /// Number of hints in all files
+    hints: u32,
+    /// Files with diagnostics, the ones with the most errors then warnings first
+    files: Vec<TsFileDiagnosticCounts>,
+}
+
 /// Viewport information
 #[derive(serde::Serialize)]
 struct TsViewportInfo {
@@ -1889,6 +2454,12 @@ fn op_fresh_get_buffer_info(state: &mut OpState, buffer_id: u32) -> Option<TsBuf
                         .unwrap_or_default(),
                     modified: info.modified,
                     length: info.length as u32,
+                    writable: info.read_only_reason.is_none(),
+                    read_only_reason: info
+                        .read_only_reason
+                        .map(ReadOnlyReason::as_str)
+                        .map(String::from),
+                    pinned: info.pinned,
                 });
             }
         };
@@ -1949,6 +2520,12 @@ fn op_fresh_list_buffers(state: &mut OpState) -> Vec<TsBufferInfo> {
                         .unwrap_or_default(),
                     modified: info.modified,
                     length: info.length as u32,
+                    writable: info.read_only_reason.is_none(),
+                    read_only_reason: info
+                        .read_only_reason
+                        .map(ReadOnlyReason::as_str)
+                        .map(String::from),
+                    pinned: info.pinned,
                 })
                 .collect();
         };
@@ -1998,6 +2575,49 @@ fn op_fresh_get_all_diagnostics(state: &mut OpState) -> Vec<TsDiagnostic> {
     Vec::new()
 }
 
+/// Get the diagnostic counts of the workspace and of each file
+///
+/// Every spelling of a file's URI counts towards one entry. Cheaper than
+/// getAllDiagnostics when only the numbers are needed.
+/// @returns DiagnosticsSummary with totals and per-file counts
+#[op2]
+#[serde]
+fn op_fresh_get_diagnostics_summary(state: &mut OpState) -> TsDiagnosticsSummary {
+    let mut result = TsDiagnosticsSummary {
+        errors: 0,
+        warnings: 0,
+        infos: 0,
+        hints: 0,
+        files: Vec::new(),
+    };
+    if let Some(runtime_state) = state.try_borrow::<Rc<RefCell<TsRuntimeState>>>() {
+        let runtime_state = runtime_state.borrow();
+        if let Ok(snapshot) = runtime_state.state_snapshot.read() {
+            let summary = &snapshot.diagnostics_summary;
+            result.errors = summary.total.errors as u32;
+            result.warnings = summary.total.warnings as u32;
+            result.infos = summary.total.infos as u32;
+            result.hints = summary.total.hints as u32;
+            result.files = summary
+                .files
+                .iter()
+                .map(|file| TsFileDiagnosticCounts {
+                    uri: file.uri.clone(),
+                    path: file
+                        .file
+                        .path()
+                        .map(|path| path.to_string_lossy().to_string()),
+                    errors: file.counts.errors as u32,
+                    warnings: file.counts.warnings as u32,
+                    infos: file.counts.infos as u32,
+                    hints: file.counts.hints as u32,
+                })
+                .collect();
+        };
+    }
+    result
+}
+
 /// Get primary cursor with selection info
 /// @returns CursorInfo object or null if no cursor
 #[op2]
@@ -2065,6 +2685,48 @@ fn op_fresh_get_viewport(state: &mut OpState) -> Option<TsViewportInfo> {
     None
 }
 
+/// Terminal size and capabilities
+#[derive(serde::Serialize)]
+struct TsTerminalInfo {
+    /// Terminal width in cells
+    width: u32,
+    /// Terminal height in cells
+    height: u32,
+    /// Whether 24-bit color is available
+    true_color: bool,
+    /// Width of East Asian ambiguous characters: "narrow" or "wide"
+    unicode_width_ambiguous: String,
+}
+
+/// Get terminal size and capabilities
+///
+/// Uses the same detection and `editor.ambiguous_width` setting as the renderer,
+/// so plugins can align virtual-buffer layouts with what is actually drawn.
+/// Subscribe to the "terminal_resized" hook to re-layout when the size changes.
+/// @returns TerminalInfo object
+#[op2]
+#[serde]
+fn op_fresh_get_terminal_info(state: &mut OpState) -> TsTerminalInfo {
+    if let Some(runtime_state) = state.try_borrow::<Rc<RefCell<TsRuntimeState>>>() {
+        let runtime_state = runtime_state.borrow();
+        if let Ok(snapshot) = runtime_state.state_snapshot.read() {
+            let info = &snapshot.terminal;
+            return TsTerminalInfo {
+                width: info.width as u32,
+                height: info.height as u32,
+                true_color: info.true_color,
+                unicode_width_ambiguous: info.unicode_width_ambiguous.clone(),
+            };
+        };
+    }
+    TsTerminalInfo {
+        width: 0,
+        height: 0,
+        true_color: false,
+        unicode_width_ambiguous: "narrow".to_string(),
+    }
+}
+
 /// Suggestion for prompt autocomplete
 #[derive(serde::Deserialize)]
 struct TsSuggestion {
@@ -2157,6 +2819,184 @@ fn op_fresh_set_prompt_suggestions(
     false
 }
 
+/// State of the active prompt
+#[derive(serde::Serialize)]
+struct TsPromptState {
+    /// Whether a prompt is currently open
+    active: bool,
+    /// Type identifier of a plugin prompt (null for built-in prompts or when inactive)
+    prompt_type: Option<String>,
+    /// Label displayed before the input
+    label: String,
+    /// Text typed so far
+    text: String,
+    /// Cursor position in the input (byte offset)
+    cursor: u32,
+    /// Index of the highlighted suggestion (null if none)
+    selected_suggestion_index: Option<u32>,
+}
+
+/// Get the state of the active prompt
+///
+/// Reads what the user has typed so far, outside of the prompt hooks.
+/// Reflects the editor state as of the last update, like other state queries.
+/// @returns PromptState object (active is false when no prompt is open)
+#[op2]
+#[serde]
+fn op_fresh_get_prompt_state(state: &mut OpState) -> TsPromptState {
+    if let Some(runtime_state) = state.try_borrow::<Rc<RefCell<TsRuntimeState>>>() {
+        let runtime_state = runtime_state.borrow();
+        if let Ok(snapshot) = runtime_state.state_snapshot.read() {
+            if let Some(ref prompt) = snapshot.prompt {
+                return TsPromptState {
+                    active: true,
+                    prompt_type: prompt.prompt_type.clone(),
+                    label: prompt.label.clone(),
+                    text: prompt.text.clone(),
+                    cursor: prompt.cursor as u32,
+                    selected_suggestion_index: prompt.selected_suggestion.map(|i| i as u32),
+                };
+            }
+        };
+    }
+    TsPromptState {
+        active: false,
+        prompt_type: None,
+        label: String::new(),
+        text: String::new(),
+        cursor: 0,
+        selected_suggestion_index: None,
+    }
+}
+
+/// Close the active plugin prompt, firing the "prompt_cancelled" hook
+///
+/// Only closes a plugin prompt whose type matches, so a stale close never
+/// cancels a prompt the user opened in the meantime. Built-in prompts are
+/// never closed.
+/// @param prompt_type - Type identifier to match (any plugin prompt if omitted)
+/// @returns true if the request was sent successfully
+#[op2]
+fn op_fresh_close_prompt(state: &mut OpState, #[string] prompt_type: Option<String>) -> bool {
+    if let Some(runtime_state) = state.try_borrow::<Rc<RefCell<TsRuntimeState>>>() {
+        let runtime_state = runtime_state.borrow();
+        let result = runtime_state
+            .command_sender
+            .send(PluginCommand::ClosePrompt { prompt_type });
+        return result.is_ok();
+    }
+    false
+}
+
+/// Submit the active plugin prompt as if the user selected a suggestion
+///
+/// Fires "prompt_confirmed" with the given selected_index. Ignored if no
+/// plugin prompt is open or the index is out of range.
+/// @param index - Index into the current suggestions
+/// @returns true if the request was sent successfully
+#[op2(fast)]
+fn op_fresh_accept_prompt_suggestion(state: &mut OpState, index: u32) -> bool {
+    if let Some(runtime_state) = state.try_borrow::<Rc<RefCell<TsRuntimeState>>>() {
+        let runtime_state = runtime_state.borrow();
+        let result = runtime_state
+            .command_sender
+            .send(PluginCommand::AcceptPromptSuggestion {
+                index: index as usize,
+            });
+        return result.is_ok();
+    }
+    false
+}
+
+/// State of the file explorer tree
+#[derive(serde::Serialize)]
+struct TsFileExplorerState {
+    /// Whether the explorer panel is shown
+    visible: bool,
+    /// Directory at the root of the tree
+    root: String,
+    /// Selected file or directory (null if nothing is selected)
+    selected_path: Option<String>,
+    /// Whether the selection is a directory
+    selected_is_dir: bool,
+    /// Expanded directories, including the root, sorted
+    expanded_paths: Vec<String>,
+}
+
+/// Get the file explorer root, selection and expanded directories
+///
+/// Commands registered for the "fileexplorer" context can use this to read
+/// the entry they act on. Reflects the editor state as of the last update.
+/// @returns FileExplorerState object, or null if the explorer has never been opened
+#[op2]
+#[serde]
+fn op_fresh_get_file_explorer_state(state: &mut OpState) -> Option<TsFileExplorerState> {
+    if let Some(runtime_state) = state.try_borrow::<Rc<RefCell<TsRuntimeState>>>() {
+        let runtime_state = runtime_state.borrow();
+        if let Ok(snapshot) = runtime_state.state_snapshot.read() {
+            if let Some(ref explorer) = snapshot.file_explorer {
+                return Some(TsFileExplorerState {
+                    visible: explorer.visible,
+                    root: explorer.root.to_string_lossy().to_string(),
+                    selected_path: explorer
+                        .selected_path
+                        .as_ref()
+                        .map(|p| p.to_string_lossy().to_string()),
+                    selected_is_dir: explorer.selected_is_dir,
+                    expanded_paths: explorer
+                        .expanded_paths
+                        .iter()
+                        .map(|p| p.to_string_lossy().to_string())
+                        .collect(),
+                });
+            }
+        };
+    }
+    None
+}
+
+/// Expand the parent directories of a path in the file explorer and select it
+///
+/// Same as the explorer following the active file, but works while the
+/// explorer is hidden. Paths outside the explorer root are ignored. Fires
+/// "file_explorer_selection_changed" once the entry is selected.
+/// @param path - File or directory, relative to the working directory unless absolute
+/// @returns true if the request was sent successfully
+#[op2(fast)]
+fn op_fresh_reveal_in_file_explorer(state: &mut OpState, #[string] path: String) -> bool {
+    if let Some(runtime_state) = state.try_borrow::<Rc<RefCell<TsRuntimeState>>>() {
+        let runtime_state = runtime_state.borrow();
+        let result = runtime_state
+            .command_sender
+            .send(PluginCommand::RevealInFileExplorer {
+                path: PathBuf::from(path),
+            });
+        return result.is_ok();
+    }
+    false
+}
+
+/// Show a different directory at the root of the file explorer
+///
+/// The working directory, LSP servers and plugins stay as they are. The path
+/// is checked like a project switch: anything but an existing directory is
+/// rejected with an error in the status bar.
+/// @param path - Directory, relative to the working directory unless absolute
+/// @returns true if the request was sent successfully
+#[op2(fast)]
+fn op_fresh_set_file_explorer_root(state: &mut OpState, #[string] path: String) -> bool {
+    if let Some(runtime_state) = state.try_borrow::<Rc<RefCell<TsRuntimeState>>>() {
+        let runtime_state = runtime_state.borrow();
+        let result = runtime_state
+            .command_sender
+            .send(PluginCommand::SetFileExplorerRoot {
+                path: PathBuf::from(path),
+            });
+        return result.is_ok();
+    }
+    false
+}
+
 /// Read entire file contents as UTF-8 string
 ///
 /// Throws if file doesn't exist, isn't readable, or isn't valid UTF-8.
@@ -2253,6 +3093,46 @@ fn op_fresh_get_cwd(state: &mut OpState) -> String {
         .unwrap_or_else(|_| ".".to_string())
 }
 
+/// A recently used project directory
+#[derive(serde::Serialize)]
+struct TsRecentProject {
+    /// Absolute path of the project directory
+    path: String,
+    /// When the project was last opened, in Unix seconds
+    last_opened: f64,
+    /// Whether the user pinned the project
+    pinned: bool,
+    /// Whether the directory still exists
+    exists: bool,
+}
+
+/// Get the recently used project directories
+///
+/// The list "Open Recent Project" offers: pinned projects first, then the
+/// rest, each most recently opened first. Read-only; the editor records
+/// projects as they are opened.
+/// @returns Array of RecentProject objects
+#[op2]
+#[serde]
+fn op_fresh_get_recent_projects(state: &mut OpState) -> Vec<TsRecentProject> {
+    if let Some(runtime_state) = state.try_borrow::<Rc<RefCell<TsRuntimeState>>>() {
+        let runtime_state = runtime_state.borrow();
+        if let Ok(snapshot) = runtime_state.state_snapshot.read() {
+            return snapshot
+                .recent_projects
+                .iter()
+                .map(|project| TsRecentProject {
+                    path: project.path.to_string_lossy().to_string(),
+                    last_opened: project.last_opened as f64,
+                    pinned: project.pinned,
+                    exists: project.path.is_dir(),
+                })
+                .collect();
+        };
+    }
+    Vec::new()
+}
+
 /// Join path segments using the OS path separator
 ///
 /// Handles empty segments and normalizes separators.
@@ -2406,6 +3286,90 @@ fn op_fresh_read_dir(
     Ok(result)
 }
 
+/// File found by compareDirectories
+#[derive(serde::Serialize)]
+struct DirCompareFile {
+    /// Path relative to both directories, with `/` separators
+    path: String,
+    /// "only-left", "only-right", "different" or "identical"
+    status: String,
+}
+
+/// Result of compareDirectories
+#[derive(serde::Serialize)]
+struct DirCompareResult {
+    /// Left directory, resolved
+    left: String,
+    /// Right directory, resolved
+    right: String,
+    /// Files of both trees, sorted by path
+    files: Vec<DirCompareFile>,
+}
+
+/// Compare two directory trees file by file
+///
+/// Walks both trees, skipping hidden and ignored files like the editor's
+/// "Compare Directories" command, and compares the files found on both
+/// sides by size, then by content. Throws if either path is not a directory.
+/// @param left - Directory path (absolute or relative to cwd)
+/// @param right - Directory path (absolute or relative to cwd)
+/// @example
+/// const result = await editor.compareDirectories("out.orig", "out");
+/// const changed = result.files.filter(f => f.status !== "identical");
+#[op2(async)]
+#[serde]
+async fn op_fresh_compare_directories(
+    state: Rc<RefCell<OpState>>,
+    #[string] left: String,
+    #[string] right: String,
+) -> Result<DirCompareResult, JsErrorBox> {
+    // Resolve relative paths against the editor's working directory
+    let working_dir = {
+        let op_state = state.borrow();
+        op_state
+            .try_borrow::<Rc<RefCell<TsRuntimeState>>>()
+            .and_then(|runtime_state| {
+                let runtime_state = runtime_state.borrow();
+                runtime_state
+                    .state_snapshot
+                    .read()
+                    .ok()
+                    .map(|snapshot| snapshot.working_dir.clone())
+            })
+    };
+    let resolve = |path: &str| match &working_dir {
+        Some(working_dir) => working_dir.join(path),
+        None => std::path::PathBuf::from(path),
+    };
+    let (left, right) = (resolve(&left), resolve(&right));
+
+    let comparison = tokio::task::spawn_blocking(move || {
+        crate::services::dir_compare::compare_dirs(
+            &left,
+            &right,
+            &crate::services::worker_pool::CancellationToken::new(),
+            &std::sync::atomic::AtomicUsize::new(0),
+        )
+    })
+    .await
+    .map_err(|e| JsErrorBox::generic(format!("Failed to compare directories: {}", e)))?
+    .map_err(|e| JsErrorBox::generic(format!("Failed to compare directories: {}", e)))?
+    .ok_or_else(|| JsErrorBox::generic("Directory comparison was cancelled"))?;
+
+    Ok(DirCompareResult {
+        left: comparison.left.to_string_lossy().to_string(),
+        right: comparison.right.to_string_lossy().to_string(),
+        files: comparison
+            .files
+            .into_iter()
+            .map(|file| DirCompareFile {
+                path: file.path.to_string_lossy().replace('\\', "/"),
+                status: file.status.as_str().to_string(),
+            })
+            .collect(),
+    })
+}
+
 // === Virtual Buffer Operations ===
 
 /// Entry for virtual buffer content with embedded metadata
@@ -2448,8 +3412,14 @@ struct CreateVirtualBufferOptions {
     show_cursors: Option<bool>,
     /// Disable all editing commands (default: false)
     editing_disabled: Option<bool>,
+    /// Show ANSI color codes in the entries as colors (default: false)
+    parse_ansi: Option<bool>,
     /// Enable/disable line wrapping (None = use global setting)
     line_wrap: Option<bool>,
+    /// Recreate the panel when the session is restored (requires panel_id, default: false)
+    persistent: Option<bool>,
+    /// Owning plugin; filled in by the plugin's editor object
+    plugin: Option<String>,
 }
 
 /// Create a virtual buffer in a new horizontal split below current pane
@@ -2457,6 +3427,9 @@ struct CreateVirtualBufferOptions {
 /// Use for results panels, diagnostics, logs, etc. The panel_id enables
 /// idempotent updates: if a panel with that ID exists, its content is replaced
 /// instead of creating a new split. Define the mode with defineMode first.
+/// Panels created with `persistent: true` are recreated as placeholders when
+/// the session is restored, and the "panel_restore" hook asks the plugin to
+/// fill them again.
 /// @param options - Buffer configuration
 /// @example
 /// // First define the mode with keybindings
@@ -2535,7 +3508,10 @@ async fn op_fresh_create_virtual_buffer_in_split(
                 show_line_numbers: options.show_line_numbers.unwrap_or(true),
                 show_cursors: options.show_cursors.unwrap_or(true),
                 editing_disabled: options.editing_disabled.unwrap_or(false),
+                parse_ansi: options.parse_ansi.unwrap_or(false),
                 line_wrap: options.line_wrap,
+                persistent: options.persistent.unwrap_or(false),
+                plugin: options.plugin,
                 request_id: Some(request_id),
             })
             .map_err(|_| JsErrorBox::generic("Failed to send command"))?;
@@ -2584,6 +3560,8 @@ struct CreateVirtualBufferInExistingSplitOptions {
     show_cursors: Option<bool>,
     /// Whether editing is disabled for this buffer (default false)
     editing_disabled: Option<bool>,
+    /// Show ANSI color codes in the entries as colors (default: false)
+    parse_ansi: Option<bool>,
     /// Enable/disable line wrapping (None = use global setting)
     line_wrap: Option<bool>,
 }
@@ -2643,6 +3621,7 @@ async fn op_fresh_create_virtual_buffer_in_existing_split(
                 show_line_numbers: options.show_line_numbers.unwrap_or(true),
                 show_cursors: options.show_cursors.unwrap_or(true),
                 editing_disabled: options.editing_disabled.unwrap_or(false),
+                parse_ansi: options.parse_ansi.unwrap_or(false),
                 line_wrap: options.line_wrap,
                 request_id: Some(request_id),
             })
@@ -2684,6 +3663,8 @@ struct CreateVirtualBufferInCurrentSplitOptions {
     show_cursors: Option<bool>,
     /// Whether editing is disabled for this buffer (default false)
     editing_disabled: Option<bool>,
+    /// Show ANSI color codes in the entries as colors (default: false)
+    parse_ansi: Option<bool>,
 }
 
 /// Create a virtual buffer in the current split as a new tab
@@ -2742,6 +3723,7 @@ async fn op_fresh_create_virtual_buffer(
                 show_line_numbers: options.show_line_numbers.unwrap_or(false),
                 show_cursors: options.show_cursors.unwrap_or(true),
                 editing_disabled: options.editing_disabled.unwrap_or(false),
+                parse_ansi: options.parse_ansi.unwrap_or(false),
                 request_id: Some(request_id),
             })
             .map_err(|_| JsErrorBox::generic("Failed to send command"))?;
@@ -2765,8 +3747,104 @@ async fn op_fresh_create_virtual_buffer(
     }
 }
 
+/// Options for createBuffer
+#[derive(Default, serde::Deserialize)]
+struct CreateBufferOptions {
+    /// Initial text; the buffer starts modified unless it's empty
+    content: Option<String>,
+    /// Language for highlighting, a key of the `languages` config (e.g., "rust")
+    language: Option<String>,
+    /// Name shown in the tab and buffer switcher; without `language`, its extension picks the highlighting
+    name_hint: Option<String>,
+}
+
+/// Options for createBufferFromFile
+#[derive(Default, serde::Deserialize)]
+struct CreateBufferFromFileOptions {
+    /// Language for highlighting, a key of the `languages` config (default: from the template's name)
+    language: Option<String>,
+    /// Name shown in the tab and buffer switcher (default: the template's file name)
+    name_hint: Option<String>,
+}
+
+/// Ask the editor for an untitled buffer and wait for its ID
+async fn create_buffer(
+    state: &Rc<RefCell<OpState>>,
+    content: String,
+    language: Option<String>,
+    name_hint: Option<String>,
+) -> Result<u32, JsErrorBox> {
+    let response = request_response(state, |request_id| PluginCommand::CreateBuffer {
+        content,
+        language,
+        name_hint,
+        request_id,
+    })
+    .await?;
+
+    match response {
+        crate::services::plugins::api::PluginResponse::BufferCreated { buffer_id, .. } => buffer_id
+            .map(|buffer_id| buffer_id.0 as u32)
+            .map_err(JsErrorBox::generic),
+        _ => Err(JsErrorBox::generic("Unexpected response type")),
+    }
+}
+
+/// Create an ordinary untitled buffer in the active split
+///
+/// The buffer is like one the user creates with New File: it has no path,
+/// so saving asks for one, and it starts modified unless it's empty.
+/// @param options - Initial content, language and name
+/// @returns Promise resolving to the buffer ID; rejects for an unknown language
+/// @example
+/// const id = await editor.createBuffer({
+///   content: "fn main() {\n}\n",
+///   language: "rust",
+///   name_hint: "main.rs"
+/// });
+#[op2(async)]
+async fn op_fresh_create_buffer(
+    state: Rc<RefCell<OpState>>,
+    #[serde] options: Option<CreateBufferOptions>,
+) -> Result<u32, JsErrorBox> {
+    let options = options.unwrap_or_default();
+    create_buffer(
+        &state,
+        options.content.unwrap_or_default(),
+        options.language,
+        options.name_hint,
+    )
+    .await
+}
+
+/// Create an untitled buffer holding the text of a template file
+///
+/// The buffer isn't bound to the template: saving asks for a path.
+/// @param path - Template file (absolute or relative to cwd)
+/// @param options - Language and name, both taken from the template's file name by default
+/// @returns Promise resolving to the buffer ID; rejects if the file can't be read
+/// @example
+/// const id = await editor.createBufferFromFile("templates/component.tsx");
+#[op2(async)]
+async fn op_fresh_create_buffer_from_file(
+    state: Rc<RefCell<OpState>>,
+    #[string] path: String,
+    #[serde] options: Option<CreateBufferFromFileOptions>,
+) -> Result<u32, JsErrorBox> {
+    let options = options.unwrap_or_default();
+    let content = tokio::fs::read_to_string(&path)
+        .await
+        .map_err(|e| JsErrorBox::generic(format!("Failed to read file {}: {}", path, e)))?;
+    let name_hint = options.name_hint.or_else(|| {
+        std::path::Path::new(&path)
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+    });
+    create_buffer(&state, content, options.language, name_hint).await
+}
+
 /// Send an arbitrary LSP request and receive the raw JSON response
-/// @param language - Language ID (e.g., "cpp")
+/// @param language - Language ID (e.g., "cpp"), or "language/name" for one of a language's several servers
 /// @param method - Full LSP method (e.g., "textDocument/switchSourceHeader")
 /// @param params - Optional request payload
 /// @returns Promise resolving to the JSON response value
@@ -2834,6 +3912,10 @@ async fn op_fresh_send_lsp_request(
 }
 
 /// Define a buffer mode with keybindings
+///
+/// A mode's bindings sit below popups, prompts and completion in the input
+/// focus stack: while one of those is open it gets the keys, and the mode
+/// only sees keys that reach normal editing.
 /// @param name - Mode name (e.g., "diagnostics-list")
 /// @param parent - Parent mode name for inheritance (e.g., "special"), or null
 /// @param bindings - Array of [key_string, command_name] pairs
@@ -2851,6 +3933,7 @@ fn op_fresh_define_mode(
     #[string] parent: String,
     #[serde] bindings: Vec<(String, String)>,
     read_only: bool,
+    #[string] plugin_name: String,
 ) -> bool {
     if let Some(runtime_state) = state.try_borrow::<Rc<RefCell<TsRuntimeState>>>() {
         let runtime_state = runtime_state.borrow();
@@ -2860,6 +3943,11 @@ fn op_fresh_define_mode(
         } else {
             Some(parent)
         };
+        let plugin = if plugin_name.is_empty() {
+            None
+        } else {
+            Some(plugin_name)
+        };
         let result = runtime_state
             .command_sender
             .send(PluginCommand::DefineMode {
@@ -2867,6 +3955,7 @@ fn op_fresh_define_mode(
                 parent,
                 bindings,
                 read_only,
+                plugin,
             });
         return result.is_ok();
     }
@@ -2998,50 +4087,151 @@ fn op_fresh_set_split_ratio(state: &mut OpState, split_id: u32, ratio: f64) -> b
     false
 }
 
-/// Distribute all visible splits evenly
-/// This adjusts the ratios of all container splits so each leaf split gets equal space
+/// Lock or unlock input to a split
+///
+/// While a split is locked, edits typed with it focused are refused; edits
+/// made through the plugin API still apply. Useful for review panes.
+/// @param split_id - ID of the split
+/// @param locked - true to lock, false to unlock
 /// @returns true if the command was sent successfully
 #[op2(fast)]
-fn op_fresh_distribute_splits_evenly(state: &mut OpState) -> bool {
+fn op_fresh_set_split_locked(state: &mut OpState, split_id: u32, locked: bool) -> bool {
     if let Some(runtime_state) = state.try_borrow::<Rc<RefCell<TsRuntimeState>>>() {
         let runtime_state = runtime_state.borrow();
         let result = runtime_state
             .command_sender
-            .send(PluginCommand::DistributeSplitsEvenly {
-                split_ids: vec![], // Currently ignored - distributes all splits
+            .send(PluginCommand::SetSplitLocked {
+                split_id: crate::model::event::SplitId(split_id as usize),
+                locked,
             });
         return result.is_ok();
     }
     false
 }
 
-/// Set cursor position in a buffer (also scrolls viewport to show cursor)
-/// @param buffer_id - ID of the buffer
-/// @param position - Byte offset position for the cursor
+/// Check whether input to a split is locked
+/// @param split_id - ID of the split
+/// @returns true if edits typed in the split are refused
+#[op2(fast)]
+fn op_fresh_is_split_locked(state: &mut OpState, split_id: u32) -> bool {
+    if let Some(runtime_state) = state.try_borrow::<Rc<RefCell<TsRuntimeState>>>() {
+        let runtime_state = runtime_state.borrow();
+        if let Ok(snapshot) = runtime_state.state_snapshot.read() {
+            return snapshot.locked_splits.contains(&(split_id as usize));
+        };
+    }
+    false
+}
+
+/// Show text in a split's winbar, the line at the top of the split
+///
+/// Setting a segment again with the same id replaces it; empty text removes
+/// it. Segments are drawn flush right in the order they were first set.
+/// @param split_id - ID of the split
+/// @param id - Identifies the segment among the split's segments
+/// @param text - Text to show, empty to remove the segment
+/// @param priority - Segments with a lower priority are dropped first when the winbar is too narrow
+/// @param r - Red color component (0-255), -1 for the winbar's own color
+/// @param g - Green color component (0-255), -1 for the winbar's own color
+/// @param b - Blue color component (0-255), -1 for the winbar's own color
+/// @param color_name - Theme color used instead of r, g, b (e.g. "diagnostic.warning_fg"), empty for none
 /// @returns true if the command was sent successfully
 #[op2(fast)]
-fn op_fresh_set_buffer_cursor(state: &mut OpState, buffer_id: u32, position: u32) -> bool {
+#[allow(clippy::too_many_arguments)]
+fn op_fresh_set_winbar_segment(
+    state: &mut OpState,
+    split_id: u32,
+    #[string] id: String,
+    #[string] text: String,
+    priority: i32,
+    r: i16,
+    g: i16,
+    b: i16,
+    #[string] color_name: String,
+) -> bool {
     if let Some(runtime_state) = state.try_borrow::<Rc<RefCell<TsRuntimeState>>>() {
         let runtime_state = runtime_state.borrow();
         let result = runtime_state
             .command_sender
-            .send(PluginCommand::SetBufferCursor {
-                buffer_id: crate::model::event::BufferId(buffer_id as usize),
-                position: position as usize,
+            .send(PluginCommand::SetWinbarSegment {
+                split_id: crate::model::event::SplitId(split_id as usize),
+                id,
+                text,
+                color: decoration_bg_color(color_name, (r, g, b)),
+                priority,
             });
         return result.is_ok();
     }
     false
 }
 
-/// Get text properties at the cursor position in a buffer
-/// @param buffer_id - ID of the buffer to query
-/// @returns Array of property objects for text ranges containing the cursor
-/// @example
-/// const props = editor.getTextPropertiesAtCursor(bufferId);
-/// if (props.length > 0 && props[0].location) {
-///   editor.openFile(props[0].location.file, props[0].location.line, 0);
-/// }
+/// Distribute all visible splits evenly
+/// This adjusts the ratios of all container splits so each leaf split gets equal space
+/// @returns true if the command was sent successfully
+#[op2(fast)]
+fn op_fresh_distribute_splits_evenly(state: &mut OpState) -> bool {
+    if let Some(runtime_state) = state.try_borrow::<Rc<RefCell<TsRuntimeState>>>() {
+        let runtime_state = runtime_state.borrow();
+        let result = runtime_state
+            .command_sender
+            .send(PluginCommand::DistributeSplitsEvenly {
+                split_ids: vec![], // Currently ignored - distributes all splits
+            });
+        return result.is_ok();
+    }
+    false
+}
+
+/// Set cursor position in a buffer (also scrolls viewport to show cursor)
+/// @param buffer_id - ID of the buffer
+/// @param position - Byte offset position for the cursor
+/// @returns true if the command was sent successfully
+#[op2(fast)]
+fn op_fresh_set_buffer_cursor(state: &mut OpState, buffer_id: u32, position: u32) -> bool {
+    if let Some(runtime_state) = state.try_borrow::<Rc<RefCell<TsRuntimeState>>>() {
+        let runtime_state = runtime_state.borrow();
+        let result = runtime_state
+            .command_sender
+            .send(PluginCommand::SetBufferCursor {
+                buffer_id: crate::model::event::BufferId(buffer_id as usize),
+                position: position as usize,
+            });
+        return result.is_ok();
+    }
+    false
+}
+
+/// Go to a byte offset in a buffer, making it the active buffer
+///
+/// The offset is clamped to the buffer and the cursor lands on the character
+/// boundary at or before it, with the view centered on it.
+/// @param buffer_id - ID of the buffer
+/// @param offset - Byte offset, e.g. from `grep --byte-offset`
+/// @returns true if the offset is valid and the command was sent
+#[op2(fast)]
+fn op_fresh_goto_byte(state: &mut OpState, buffer_id: u32, offset: f64) -> bool {
+    if !offset.is_finite() || offset < 0.0 {
+        return false;
+    }
+    if let Some(runtime_state) = state.try_borrow::<Rc<RefCell<TsRuntimeState>>>() {
+        let runtime_state = runtime_state.borrow();
+        let result = runtime_state.command_sender.send(PluginCommand::GotoByte {
+            buffer_id: crate::model::event::BufferId(buffer_id as usize),
+            offset: offset as usize,
+        });
+        return result.is_ok();
+    }
+    false
+}
+
+/// Get text properties at the cursor position in a buffer
+/// @param buffer_id - ID of the buffer to query
+/// @returns Array of property objects for text ranges containing the cursor
+/// @example
+/// const props = editor.getTextPropertiesAtCursor(bufferId);
+/// if (props.length > 0 && props[0].location) {
+///   editor.openFile(props[0].location.file, props[0].location.line, 0);
+/// }
 #[op2]
 #[serde]
 fn op_fresh_get_text_properties_at_cursor(
@@ -3053,22 +4243,13 @@ fn op_fresh_get_text_properties_at_cursor(
         if let Ok(snapshot) = runtime_state.state_snapshot.read() {
             let buffer_id_key = BufferId(buffer_id as usize);
 
-            // Get cursor position for this buffer
-            let cursor_pos = match snapshot.buffer_cursor_positions.get(&buffer_id_key) {
-                Some(pos) => *pos,
-                None => return vec![],
-            };
-
-            // Get text properties for this buffer
+            // The snapshot holds just the properties at each buffer's cursor
             let properties = match snapshot.buffer_text_properties.get(&buffer_id_key) {
                 Some(props) => props,
                 None => return vec![],
             };
-
-            // Find all properties that contain the cursor position
             return properties
                 .iter()
-                .filter(|prop| prop.contains(cursor_pos))
                 .map(|prop| prop.properties.clone())
                 .collect();
         };
@@ -3109,6 +4290,40 @@ fn op_fresh_set_virtual_buffer_content(
     false
 }
 
+/// Add entries at the end of a virtual buffer's content
+///
+/// Lets a panel with many entries send them in chunks, showing the first
+/// ones while the rest are still being collected.
+/// @param buffer_id - ID of the virtual buffer
+/// @param entries - Array of text entries with properties
+/// @returns true if the entries were sent successfully
+#[op2]
+fn op_fresh_append_virtual_buffer_content(
+    state: &mut OpState,
+    buffer_id: u32,
+    #[serde] entries: Vec<TsTextPropertyEntry>,
+) -> bool {
+    if let Some(runtime_state) = state.try_borrow::<Rc<RefCell<TsRuntimeState>>>() {
+        let runtime_state = runtime_state.borrow();
+        let rust_entries: Vec<crate::primitives::text_property::TextPropertyEntry> = entries
+            .into_iter()
+            .map(|e| crate::primitives::text_property::TextPropertyEntry {
+                text: e.text,
+                properties: e.properties,
+            })
+            .collect();
+
+        let result = runtime_state
+            .command_sender
+            .send(PluginCommand::AppendVirtualBufferContent {
+                buffer_id: BufferId(buffer_id as usize),
+                entries: rust_entries,
+            });
+        return result.is_ok();
+    }
+    false
+}
+
 /// Execute a built-in editor action by name
 ///
 /// This is used by vi mode plugin to run motions and then check cursor position.
@@ -3168,13 +4383,305 @@ struct ActionSpecJs {
     count: Option<u32>,
 }
 
+/// TypeScript struct for the outcome of an action executed by a plugin
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct TsActionResult {
+    /// Whether the action exists and ran without error
+    pub ok: bool,
+    /// Why the action failed (unknown action name or execution error)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Whether the active buffer or its primary cursor position changed
+    pub cursor_moved: bool,
+    /// Whether the content of the buffer that was active changed
+    pub buffer_changed: bool,
+}
+
+impl From<crate::services::plugins::api::ActionResult> for TsActionResult {
+    fn from(result: crate::services::plugins::api::ActionResult) -> Self {
+        Self {
+            ok: result.ok,
+            error: result.error,
+            cursor_moved: result.cursor_moved,
+            buffer_changed: result.buffer_changed,
+        }
+    }
+}
+
+/// Send an action command with a fresh request ID and wait for the editor to run it
+async fn execute_actions_awaiting_result(
+    state: Rc<RefCell<OpState>>,
+    make_command: impl FnOnce(u64) -> PluginCommand,
+) -> Result<Vec<TsActionResult>, JsErrorBox> {
+    let receiver = {
+        let state = state.borrow();
+        let runtime_state = state
+            .try_borrow::<Rc<RefCell<TsRuntimeState>>>()
+            .ok_or_else(|| JsErrorBox::generic("Failed to get runtime state"))?;
+        let runtime_state = runtime_state.borrow();
+
+        // Allocate request ID
+        let request_id = {
+            let mut id = runtime_state.next_request_id.borrow_mut();
+            let current = *id;
+            *id += 1;
+            current
+        };
+
+        // Create oneshot channel for response
+        let (tx, rx) = tokio::sync::oneshot::channel();
+
+        // Store the sender
+        {
+            let mut pending = runtime_state.pending_responses.lock().unwrap();
+            pending.insert(request_id, tx);
+        }
+
+        runtime_state
+            .command_sender
+            .send(make_command(request_id))
+            .map_err(|_| JsErrorBox::generic("Failed to send action command"))?;
+
+        rx
+    };
+
+    // Wait until the editor has applied the actions
+    let response = receiver
+        .await
+        .map_err(|_| JsErrorBox::generic("Response channel closed"))?;
+
+    match response {
+        crate::services::plugins::api::PluginResponse::ActionsExecuted { results, .. } => {
+            Ok(results.into_iter().map(TsActionResult::from).collect())
+        }
+        _ => Err(JsErrorBox::generic("Unexpected response type")),
+    }
+}
+
+/// Execute a built-in editor action by name and wait for its outcome
+///
+/// Unlike executeAction, resolves only after the editor has applied the action,
+/// so a plugin can check the result before deciding what to do next (e.g. vi mode
+/// cancels a pending operator when its motion didn't move the cursor).
+/// @param action_name - Action name (e.g., "move_word_right", "move_line_end")
+/// @returns ActionResult with ok, error (if any), cursor_moved and buffer_changed
+/// @example
+/// const result = await editor.executeActionAwait("move_word_right");
+/// if (result.ok && result.cursor_moved) {
+///   // apply the operator to the range the motion covered
+/// }
+#[op2(async)]
+#[serde]
+async fn op_fresh_execute_action_await(
+    state: Rc<RefCell<OpState>>,
+    #[string] action_name: String,
+) -> Result<TsActionResult, JsErrorBox> {
+    let mut results = execute_actions_awaiting_result(state, |request_id| {
+        PluginCommand::ExecuteActionWithResult {
+            action_name,
+            request_id,
+        }
+    })
+    .await?;
+    results
+        .pop()
+        .ok_or_else(|| JsErrorBox::generic("No result for action"))
+}
+
+/// Execute multiple actions in sequence and wait for each one's outcome
+///
+/// Stops at the first action that fails, so the returned array is shorter than
+/// `actions` when one of them fails; its last entry is the failing action.
+/// @param actions - Array of {action: string, count?: number} objects
+/// @returns Array of ActionResult, one per action that was run
+#[op2(async)]
+#[serde]
+async fn op_fresh_execute_actions_await(
+    state: Rc<RefCell<OpState>>,
+    #[serde] actions: Vec<ActionSpecJs>,
+) -> Result<Vec<TsActionResult>, JsErrorBox> {
+    let actions: Vec<ActionSpec> = actions
+        .into_iter()
+        .map(|a| ActionSpec {
+            action: a.action,
+            count: a.count.unwrap_or(1),
+        })
+        .collect();
+    execute_actions_awaiting_result(state, |request_id| {
+        PluginCommand::ExecuteActionsWithResult {
+            actions,
+            request_id,
+        }
+    })
+    .await
+}
+
+/// TypeScript struct for a binding that loses to another binding of the same keys
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct TsShadowedBinding {
+    /// Action or command name
+    pub action: String,
+    /// Context the binding was defined for ("global", "normal", ...)
+    pub context: String,
+    /// Where it comes from: "keymap", "user" or "mode"
+    pub source: String,
+    /// Keymap name, for "keymap" bindings
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub keymap: Option<String>,
+    /// Mode name, for "mode" bindings
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mode: Option<String>,
+    /// Plugin that defined the mode
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub plugin: Option<String>,
+}
+
+/// TypeScript struct for one key of the effective keymap
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct TsKeymapBinding {
+    /// Key or chord as displayed (e.g. "Ctrl+S", "G G")
+    pub key: String,
+    /// Action or command name
+    pub action: String,
+    /// Human-readable description of the action
+    pub description: String,
+    /// Context the binding was defined for ("global", "normal", ...)
+    pub context: String,
+    /// Where it comes from: "keymap", "user" or "mode"
+    pub source: String,
+    /// Keymap name, for "keymap" bindings
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub keymap: Option<String>,
+    /// Mode name, for "mode" bindings
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mode: Option<String>,
+    /// Plugin that defined the mode
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub plugin: Option<String>,
+    /// Bindings of the same key that this one wins over, highest priority first
+    pub shadowed: Vec<TsShadowedBinding>,
+}
+
+/// Split a binding source into the (source, keymap, mode, plugin) fields
+fn ts_binding_source(
+    source: crate::input::keybindings::BindingSource,
+) -> (String, Option<String>, Option<String>, Option<String>) {
+    use crate::input::keybindings::BindingSource;
+    match source {
+        BindingSource::Keymap(name) => ("keymap".to_string(), Some(name), None, None),
+        BindingSource::User => ("user".to_string(), None, None, None),
+        BindingSource::Mode { mode, plugin } => ("mode".to_string(), None, Some(mode), plugin),
+    }
+}
+
+impl From<crate::input::keybindings::KeymapEntry> for TsKeymapBinding {
+    fn from(entry: crate::input::keybindings::KeymapEntry) -> Self {
+        let key = entry.key_display();
+        let (source, keymap, mode, plugin) = ts_binding_source(entry.source);
+        Self {
+            key,
+            action: entry.action,
+            description: entry.description,
+            context: entry.context.to_when_clause().to_string(),
+            source,
+            keymap,
+            mode,
+            plugin,
+            shadowed: entry
+                .shadowed
+                .into_iter()
+                .map(|shadowed| {
+                    let (source, keymap, mode, plugin) = ts_binding_source(shadowed.source);
+                    TsShadowedBinding {
+                        action: shadowed.action,
+                        context: shadowed.context.to_when_clause().to_string(),
+                        source,
+                        keymap,
+                        mode,
+                        plugin,
+                    }
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Get the effective keymap with the source of every binding
+///
+/// Lists what each key does in the given context after merging the active
+/// keymap, the user's keybindings and, for "normal" and "fileExplorer", the
+/// editor mode and the active buffer's mode, so a plugin can build its own
+/// cheat sheet. Terminal key equivalents added automatically are not listed.
+/// @param context - Context name as in `when` clauses ("normal", "prompt", ...), or null for the current one
+/// @returns Array of KeymapBinding; throws if the context name is unknown
+/// @example
+/// const bindings = await editor.getKeymap("normal");
+/// const conflicts = bindings.filter(b => b.shadowed.length > 0);
+#[op2(async)]
+#[serde]
+async fn op_fresh_get_keymap(
+    state: Rc<RefCell<OpState>>,
+    #[string] context: String,
+) -> Result<Vec<TsKeymapBinding>, JsErrorBox> {
+    let receiver = {
+        let state = state.borrow();
+        let runtime_state = state
+            .try_borrow::<Rc<RefCell<TsRuntimeState>>>()
+            .ok_or_else(|| JsErrorBox::generic("Failed to get runtime state"))?;
+        let runtime_state = runtime_state.borrow();
+
+        let request_id = {
+            let mut id = runtime_state.next_request_id.borrow_mut();
+            let current = *id;
+            *id += 1;
+            current
+        };
+
+        let (tx, rx) = tokio::sync::oneshot::channel();
+        {
+            let mut pending = runtime_state.pending_responses.lock().unwrap();
+            pending.insert(request_id, tx);
+        }
+
+        runtime_state
+            .command_sender
+            .send(PluginCommand::GetKeymap {
+                context: if context.is_empty() {
+                    None
+                } else {
+                    Some(context)
+                },
+                request_id,
+            })
+            .map_err(|_| JsErrorBox::generic("Failed to send GetKeymap command"))?;
+
+        rx
+    };
+
+    let response = receiver
+        .await
+        .map_err(|_| JsErrorBox::generic("Response channel closed"))?;
+
+    match response {
+        crate::services::plugins::api::PluginResponse::Keymap { entries, .. } => entries
+            .map(|entries| entries.into_iter().map(TsKeymapBinding::from).collect())
+            .map_err(JsErrorBox::generic),
+        _ => Err(JsErrorBox::generic("Unexpected response type")),
+    }
+}
+
 /// Get text from a buffer range
 ///
 /// Used by vi mode plugin for yank operations - reads text without deleting.
+/// Rejects ranges longer than the `editor.plugin_text_limit_bytes` setting
+/// (16 MiB by default); read large buffers with readBufferChunks.
+/// Bytes of the file that aren't valid UTF-8 read as the private use
+/// character U+10FD00 + byte (U+10FD80..U+10FDFF, 4 bytes each); writing
+/// them back keeps the original byte on save.
 /// @param buffer_id - Buffer ID
 /// @param start - Start byte offset
 /// @param end - End byte offset
-/// @returns Text content of the range, or empty string on error
+/// @returns Text content of the range
 #[op2(async)]
 #[string]
 async fn op_fresh_get_buffer_text(
@@ -3182,6 +4689,7 @@ async fn op_fresh_get_buffer_text(
     buffer_id: u32,
     start: u32,
     end: u32,
+    #[string] plugin_name: String,
 ) -> Result<String, JsErrorBox> {
     let receiver = {
         let state = state.borrow();
@@ -3214,6 +4722,7 @@ async fn op_fresh_get_buffer_text(
                 buffer_id: BufferId(buffer_id as usize),
                 start: start as usize,
                 end: end as usize,
+                plugin: plugin_name,
                 request_id,
             })
             .map_err(|_| JsErrorBox::generic("Failed to send GetBufferText command"))?;
@@ -3234,48 +4743,247 @@ async fn op_fresh_get_buffer_text(
     }
 }
 
-/// Set the global editor mode (for modal editing like vi mode)
-///
-/// When a mode is set, its keybindings take precedence over normal key handling.
-/// Pass null/undefined to clear the mode and return to normal editing.
-///
-/// @param mode - Mode name (e.g., "vi-normal") or null to clear
-/// @returns true if command was sent successfully
-#[op2]
-fn op_fresh_set_editor_mode(state: &mut OpState, #[string] mode: Option<String>) -> bool {
-    if let Some(runtime_state) = state.try_borrow::<Rc<RefCell<TsRuntimeState>>>() {
-        let runtime_state = runtime_state.borrow();
-        let result = runtime_state
-            .command_sender
-            .send(PluginCommand::SetEditorMode { mode });
-        return result.is_ok();
-    }
-    false
+/// TypeScript struct for a position in a buffer
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct TsLineCol {
+    /// Line (0-indexed)
+    pub line: u32,
+    /// Byte offset from the start of the line
+    pub column: u32,
 }
 
-/// Get the current global editor mode
-///
-/// @returns Current mode name or null if no mode is active
-#[op2]
-#[string]
-fn op_fresh_get_editor_mode(state: &mut OpState) -> Option<String> {
-    if let Some(runtime_state) = state.try_borrow::<Rc<RefCell<TsRuntimeState>>>() {
+/// Send a request and wait for the editor's response
+async fn request_response(
+    state: &Rc<RefCell<OpState>>,
+    command: impl FnOnce(u64) -> PluginCommand,
+) -> Result<crate::services::plugins::api::PluginResponse, JsErrorBox> {
+    let receiver = {
+        let state = state.borrow();
+        let runtime_state = state
+            .try_borrow::<Rc<RefCell<TsRuntimeState>>>()
+            .ok_or_else(|| JsErrorBox::generic("Failed to get runtime state"))?;
         let runtime_state = runtime_state.borrow();
-        if let Ok(snapshot) = runtime_state.state_snapshot.read() {
-            return snapshot.editor_mode.clone();
+
+        let request_id = {
+            let mut id = runtime_state.next_request_id.borrow_mut();
+            let current = *id;
+            *id += 1;
+            current
         };
-    }
-    None
-}
 
-/// TypeScript struct for action popup action
-#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
-pub struct TsActionPopupAction {
-    pub id: String,
-    pub label: String,
-}
+        let (tx, rx) = tokio::sync::oneshot::channel();
+        {
+            let mut pending = runtime_state.pending_responses.lock().unwrap();
+            pending.insert(request_id, tx);
+        }
 
-/// TypeScript struct for action popup options
+        runtime_state
+            .command_sender
+            .send(command(request_id))
+            .map_err(|_| JsErrorBox::generic("Failed to send command"))?;
+
+        rx
+    };
+
+    receiver
+        .await
+        .map_err(|_| JsErrorBox::generic("Response channel closed"))
+}
+
+/// Convert a byte offset to a line and column
+///
+/// Uses the buffer's line index, so it costs the same anywhere in a file.
+/// Lines are 0-indexed and end at "\n"; the column counts bytes.
+/// @param buffer_id - Buffer ID
+/// @param byte - Byte offset, up to the buffer length
+/// @returns The LineCol of the byte; throws if the offset is past the end or the buffer is too large to have a line index
+/// @example
+/// const { line, column } = await editor.byteToLineCol(bufferId, editor.getCursorPosition());
+#[op2(async)]
+#[serde]
+async fn op_fresh_byte_to_line_col(
+    state: Rc<RefCell<OpState>>,
+    buffer_id: u32,
+    byte: u32,
+) -> Result<TsLineCol, JsErrorBox> {
+    let response = request_response(&state, |request_id| PluginCommand::ByteToLineCol {
+        buffer_id: BufferId(buffer_id as usize),
+        byte: byte as usize,
+        request_id,
+    })
+    .await?;
+
+    match response {
+        crate::services::plugins::api::PluginResponse::LineCol { position, .. } => position
+            .map(|(line, column)| TsLineCol {
+                line: line as u32,
+                column: column as u32,
+            })
+            .map_err(JsErrorBox::generic),
+        _ => Err(JsErrorBox::generic("Unexpected response type")),
+    }
+}
+
+/// Convert a line and column to a byte offset
+///
+/// Uses the buffer's line index, so it costs the same anywhere in a file.
+/// @param buffer_id - Buffer ID
+/// @param line - Line (0-indexed)
+/// @param column - Byte offset in the line, clamped to the end of the line (before its "\n")
+/// @returns Byte offset; throws if the line doesn't exist or the buffer is too large to have a line index
+/// @example
+/// const start = await editor.lineColToByte(bufferId, 41, 0);
+#[op2(async)]
+async fn op_fresh_line_col_to_byte(
+    state: Rc<RefCell<OpState>>,
+    buffer_id: u32,
+    line: u32,
+    column: u32,
+) -> Result<u32, JsErrorBox> {
+    let response = request_response(&state, |request_id| PluginCommand::LineColToByte {
+        buffer_id: BufferId(buffer_id as usize),
+        line: line as usize,
+        column: column as usize,
+        request_id,
+    })
+    .await?;
+
+    match response {
+        crate::services::plugins::api::PluginResponse::ByteOffset { offset, .. } => offset
+            .map(|offset| offset as u32)
+            .map_err(JsErrorBox::generic),
+        _ => Err(JsErrorBox::generic("Unexpected response type")),
+    }
+}
+
+/// Read a whole buffer in pieces of at most `chunk_size` bytes
+///
+/// The handler is called with `{read_id, buffer_id, offset, text, done}` for
+/// each piece, in order, spread over editor ticks so a large buffer is never
+/// copied at once. Pieces end on character boundaries; the last one has
+/// `done` set. Closing the buffer ends the read early with an empty last piece.
+/// @param buffer_id - Buffer ID
+/// @param chunk_size - Most bytes in one piece (capped at `editor.plugin_text_limit_bytes`)
+/// @param handler_name - Name of globalThis function to call with each piece
+/// @returns Read ID, as passed to the handler
+/// @example
+/// let text = "";
+/// globalThis.onChunk = (chunk) => {
+/// text += chunk.text;
+/// if (chunk.done) editor.setStatus(`${text.length} characters`);
+/// };
+/// editor.readBufferChunks(editor.getActiveBufferId(), 1 << 20, "onChunk");
+#[op2(fast)]
+fn op_fresh_read_buffer_chunks(
+    state: &mut OpState,
+    buffer_id: u32,
+    chunk_size: u32,
+    #[string] handler_name: String,
+    #[string] plugin_name: String,
+) -> Result<u32, JsErrorBox> {
+    let Some(runtime_state) = state.try_borrow::<Rc<RefCell<TsRuntimeState>>>() else {
+        return Err(JsErrorBox::generic("Plugin runtime is not available"));
+    };
+    if chunk_size == 0 {
+        return Err(JsErrorBox::generic(
+            "readBufferChunks needs a chunk size of at least 1 byte",
+        ));
+    }
+    let runtime_state = runtime_state.borrow();
+
+    let read_id = {
+        let mut next = runtime_state.next_read_id.borrow_mut();
+        let id = *next;
+        *next += 1;
+        id
+    };
+    runtime_state.event_handlers.borrow_mut().insert(
+        buffer_chunks_hook_name(read_id),
+        vec![EventHandlerInfo {
+            handler: handler_name,
+            plugin: plugin_name.clone(),
+        }],
+    );
+
+    let _ = runtime_state
+        .command_sender
+        .send(PluginCommand::ReadBufferChunks {
+            buffer_id: BufferId(buffer_id as usize),
+            chunk_size: chunk_size as usize,
+            read_id,
+            plugin: plugin_name,
+        });
+    Ok(read_id)
+}
+
+/// Set the global editor mode (for modal editing like vi mode)
+///
+/// When a mode is set, its keybindings take precedence over normal key handling.
+/// Pass null/undefined to clear the mode and return to normal editing.
+///
+/// @param mode - Mode name (e.g., "vi-normal") or null to clear
+/// @returns true if command was sent successfully
+#[op2]
+fn op_fresh_set_editor_mode(state: &mut OpState, #[string] mode: Option<String>) -> bool {
+    if let Some(runtime_state) = state.try_borrow::<Rc<RefCell<TsRuntimeState>>>() {
+        let runtime_state = runtime_state.borrow();
+        let result = runtime_state
+            .command_sender
+            .send(PluginCommand::SetEditorMode { mode });
+        return result.is_ok();
+    }
+    false
+}
+
+/// Get the current global editor mode
+///
+/// @returns Current mode name or null if no mode is active
+#[op2]
+#[string]
+fn op_fresh_get_editor_mode(state: &mut OpState) -> Option<String> {
+    if let Some(runtime_state) = state.try_borrow::<Rc<RefCell<TsRuntimeState>>>() {
+        let runtime_state = runtime_state.borrow();
+        if let Ok(snapshot) = runtime_state.state_snapshot.read() {
+            return snapshot.editor_mode.clone();
+        };
+    }
+    None
+}
+
+/// Set the terminal cursor style
+///
+/// Overrides the `ui.cursor` config until cleared, for plugin modes the
+/// config doesn't cover. Pass null/undefined to go back to the configured
+/// styles.
+///
+/// @param style - "default", "blinking_block", "steady_block", "blinking_bar",
+///   "steady_bar", "blinking_underline", "steady_underline", or null to clear
+/// @returns true if the style is valid and the command was sent
+#[op2]
+fn op_fresh_set_cursor_style(state: &mut OpState, #[string] style: Option<String>) -> bool {
+    if let Some(name) = style.as_deref() {
+        if crate::config::CursorStyle::from_str(name).is_none() {
+            return false;
+        }
+    }
+    if let Some(runtime_state) = state.try_borrow::<Rc<RefCell<TsRuntimeState>>>() {
+        let runtime_state = runtime_state.borrow();
+        let result = runtime_state
+            .command_sender
+            .send(PluginCommand::SetCursorStyle { style });
+        return result.is_ok();
+    }
+    false
+}
+
+/// TypeScript struct for action popup action
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct TsActionPopupAction {
+    pub id: String,
+    pub label: String,
+}
+
+/// TypeScript struct for action popup options
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct TsActionPopupOptions {
     pub id: String,
@@ -3315,83 +5023,624 @@ fn op_fresh_show_action_popup(state: &mut OpState, #[serde] options: TsActionPop
     false
 }
 
+/// TypeScript struct for anchored popup options
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct TsAnchoredPopupOptions {
+    /// Buffer the popup points into
+    pub buffer_id: u32,
+    /// Byte offset the popup is anchored to
+    pub position: usize,
+    /// Text lines, wrapped to max_width
+    pub lines: Vec<String>,
+    /// Widest the popup may be, in columns (default 60)
+    pub max_width: Option<u16>,
+    /// Close the popup after this long (default: when the cursor or mouse moves away)
+    pub timeout_ms: Option<u64>,
+}
+
+/// Show a small borderless popup anchored to a byte position
+///
+/// It goes below the position's line, or above it near the bottom of the
+/// screen, and closes when the cursor or mouse leaves the word there, after
+/// timeout_ms, or with closeAnchoredPopup. Only one is shown at a time:
+/// a new one replaces the previous one, from any plugin. Not shown while the
+/// LSP hover popup is open.
+/// @param options - buffer_id, position, lines, optional max_width and timeout_ms
+/// @example
+/// editor.on("mouse_moved_in_buffer", "onMouseMoved");
+/// globalThis.onMouseMoved = (data) => {
+///   editor.showAnchoredPopup({ buffer_id: data.buffer_id, position: data.byte_offset, lines: ["#ff0000"] });
+/// };
+#[op2]
+fn op_fresh_show_anchored_popup(
+    state: &mut OpState,
+    #[serde] options: TsAnchoredPopupOptions,
+) -> bool {
+    if let Some(runtime_state) = state.try_borrow::<Rc<RefCell<TsRuntimeState>>>() {
+        let runtime_state = runtime_state.borrow();
+        let result = runtime_state
+            .command_sender
+            .send(PluginCommand::ShowAnchoredPopup {
+                buffer_id: BufferId(options.buffer_id as usize),
+                position: options.position,
+                lines: options.lines,
+                max_width: options.max_width.unwrap_or(60),
+                timeout_ms: options.timeout_ms,
+            });
+        return result.is_ok();
+    }
+    false
+}
+
+/// Close the popup shown with showAnchoredPopup
+#[op2(fast)]
+fn op_fresh_close_anchored_popup(state: &mut OpState) -> bool {
+    if let Some(runtime_state) = state.try_borrow::<Rc<RefCell<TsRuntimeState>>>() {
+        let runtime_state = runtime_state.borrow();
+        return runtime_state
+            .command_sender
+            .send(PluginCommand::CloseAnchoredPopup)
+            .is_ok();
+    }
+    false
+}
+
+/// TypeScript struct for notification options
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct TsNotifyOptions {
+    pub id: Option<String>,
+    pub message: String,
+    pub severity: Option<String>,
+    pub timeout_ms: Option<u64>,
+    pub actions: Option<Vec<TsActionPopupAction>>,
+}
+
+/// Show a toast notification above the status bar
+///
+/// Notifications stack (newest at the bottom) and go away after their
+/// timeout. Choosing a button, or a notification with buttons going away
+/// unanswered, fires the ActionPopupResult hook with the notification id.
+/// @param options - Message, severity ("info", "warning", "error"), timeout_ms (0 = until dismissed), optional id and actions
+/// @returns The notification id
+#[op2]
+#[string]
+fn op_fresh_notify(state: &mut OpState, #[serde] options: TsNotifyOptions) -> String {
+    let notification_id = options
+        .id
+        .unwrap_or_else(crate::view::notifications::next_notification_id);
+    if let Some(runtime_state) = state.try_borrow::<Rc<RefCell<TsRuntimeState>>>() {
+        let runtime_state = runtime_state.borrow();
+        let actions = options
+            .actions
+            .unwrap_or_default()
+            .into_iter()
+            .map(|a| ActionPopupAction {
+                id: a.id,
+                label: a.label,
+            })
+            .collect();
+        let _ = runtime_state.command_sender.send(PluginCommand::Notify {
+            notification_id: notification_id.clone(),
+            message: options.message,
+            severity: crate::view::notifications::NotificationSeverity::parse(
+                options.severity.as_deref().unwrap_or("info"),
+            ),
+            timeout_ms: options.timeout_ms,
+            actions,
+        });
+    }
+    notification_id
+}
+
+/// TypeScript struct for the byte range of a pending edit
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct TsPendingEditRange {
+    pub start: usize,
+    pub end: usize,
+}
+
+/// TypeScript struct for a proposed edit in a pending edits review
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct TsPendingEdit {
+    /// File path (absolute, or relative to the working directory)
+    pub file: String,
+    /// Byte range in the file that will be replaced
+    pub range: TsPendingEditRange,
+    /// Text currently in the range; edits whose text no longer matches are skipped
+    pub old_text: String,
+    /// Replacement text
+    pub new_text: String,
+    /// Whether the edit starts checked (default: true)
+    pub enabled: Option<bool>,
+}
+
+/// TypeScript struct for pending edits review options
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct TsReviewPendingEditsOptions {
+    /// Title shown in the review header
+    pub title: String,
+    /// Proposed edits
+    pub edits: Vec<TsPendingEdit>,
+    /// Apply the selected edits when the user confirms (default: true)
+    pub apply: Option<bool>,
+}
+
+/// Open the pending edits review buffer for a set of proposed edits
+///
+/// Lists every edit grouped by file. The user toggles edits (Space) or whole
+/// files (f), expands context (Tab), then confirms (Enter) or cancels (q).
+/// Unless `apply` is false, confirmed edits are applied atomically per buffer.
+/// @param options - Review title, edits, and whether to apply on confirm
+/// @returns The edits the user kept, or null if the review was cancelled
+/// @example
+/// const kept = await editor.reviewPendingEdits({
+///   title: "Rename foo to bar",
+///   edits: [{ file: "src/main.rs", range: { start: 10, end: 13 }, old_text: "foo", new_text: "bar", enabled: true }]
+/// });
+#[op2(async)]
+#[serde]
+async fn op_fresh_review_pending_edits(
+    state: Rc<RefCell<OpState>>,
+    #[serde] options: TsReviewPendingEditsOptions,
+) -> Result<Option<Vec<TsPendingEdit>>, JsErrorBox> {
+    use crate::model::pending_edits::PendingEdit;
+
+    let receiver = {
+        let state = state.borrow();
+        let runtime_state = state
+            .try_borrow::<Rc<RefCell<TsRuntimeState>>>()
+            .ok_or_else(|| JsErrorBox::generic("Failed to get runtime state"))?;
+        let runtime_state = runtime_state.borrow();
+
+        // Allocate request ID
+        let request_id = {
+            let mut id = runtime_state.next_request_id.borrow_mut();
+            let current = *id;
+            *id += 1;
+            current
+        };
+
+        // Create oneshot channel for response
+        let (tx, rx) = tokio::sync::oneshot::channel();
+
+        // Store the sender
+        {
+            let mut pending = runtime_state.pending_responses.lock().unwrap();
+            pending.insert(request_id, tx);
+        }
+
+        let edits: Vec<PendingEdit> = options
+            .edits
+            .into_iter()
+            .map(|e| PendingEdit {
+                file: std::path::PathBuf::from(e.file),
+                range: e.range.start..e.range.end,
+                old_text: e.old_text,
+                new_text: e.new_text,
+                enabled: e.enabled.unwrap_or(true),
+            })
+            .collect();
+
+        runtime_state
+            .command_sender
+            .send(PluginCommand::ReviewPendingEdits {
+                title: options.title,
+                edits,
+                apply: options.apply.unwrap_or(true),
+                request_id,
+            })
+            .map_err(|_| JsErrorBox::generic("Failed to send ReviewPendingEdits command"))?;
+
+        rx
+    };
+
+    // Wait for the user to finish the review
+    let response = receiver
+        .await
+        .map_err(|_| JsErrorBox::generic("Response channel closed"))?;
+
+    match response {
+        crate::services::plugins::api::PluginResponse::PendingEditsReviewed { edits, .. } => {
+            Ok(edits.map(|edits| {
+                edits
+                    .into_iter()
+                    .map(|e| TsPendingEdit {
+                        file: e.file.to_string_lossy().to_string(),
+                        range: TsPendingEditRange {
+                            start: e.range.start,
+                            end: e.range.end,
+                        },
+                        old_text: e.old_text,
+                        new_text: e.new_text,
+                        enabled: Some(e.enabled),
+                    })
+                    .collect()
+            }))
+        }
+        _ => Err(JsErrorBox::generic("Unexpected response type")),
+    }
+}
+
+/// Set a single config value and persist it to the user config file
+///
+/// Plugins should use this rather than writing the config file themselves: the
+/// editor writes atomically, keeps a backup, and asks the user before
+/// overwriting changes made to the file since it was loaded.
+/// @param path - Dot-separated key path (e.g., "editor.tab_size", "lsp.rust.enabled")
+/// @param value - New value for the key
+/// @returns true if the request was sent
+#[op2]
+fn op_fresh_set_config_value(
+    state: &mut OpState,
+    #[string] path: String,
+    #[serde] value: serde_json::Value,
+) -> bool {
+    if let Some(runtime_state) = state.try_borrow::<Rc<RefCell<TsRuntimeState>>>() {
+        let runtime_state = runtime_state.borrow();
+        let result = runtime_state
+            .command_sender
+            .send(PluginCommand::SetConfigValue { path, value });
+        return result.is_ok();
+    }
+    false
+}
+
 /// Disable LSP for a specific language and persist to config
 ///
 /// This is used by LSP helper plugins to let users disable LSP for languages
 /// where the server is not available or not working.
 /// @param language - The language to disable LSP for (e.g., "python", "rust")
+/// @param server - Name of the one server to disable when the language has several, or null for all
+#[op2]
+fn op_fresh_disable_lsp_for_language(
+    state: &mut OpState,
+    #[string] language: String,
+    #[string] server: Option<String>,
+) -> bool {
+    if let Some(runtime_state) = state.try_borrow::<Rc<RefCell<TsRuntimeState>>>() {
+        let runtime_state = runtime_state.borrow();
+        let result = runtime_state
+            .command_sender
+            .send(PluginCommand::DisableLspForLanguage { language, server });
+        return result.is_ok();
+    }
+    false
+}
+
+/// Create a scroll sync group for anchor-based synchronized scrolling
+///
+/// Used for side-by-side diff views where two panes need to scroll together.
+/// The plugin provides the group ID (must be unique per plugin).
+#[op2(fast)]
+fn op_fresh_create_scroll_sync_group(
+    state: &mut OpState,
+    group_id: u32,
+    left_split: u32,
+    right_split: u32,
+) -> bool {
+    use crate::model::event::SplitId;
+
+    if let Some(runtime_state) = state.try_borrow::<Rc<RefCell<TsRuntimeState>>>() {
+        let runtime_state = runtime_state.borrow();
+        let result = runtime_state
+            .command_sender
+            .send(PluginCommand::CreateScrollSyncGroup {
+                group_id,
+                left_split: SplitId(left_split as usize),
+                right_split: SplitId(right_split as usize),
+            });
+        return result.is_ok();
+    }
+    false
+}
+
+/// Set sync anchors for a scroll sync group
+///
+/// Anchors map corresponding line numbers between left and right buffers.
+/// Each anchor is a tuple of (left_line, right_line).
+#[op2]
+fn op_fresh_set_scroll_sync_anchors(
+    state: &mut OpState,
+    group_id: u32,
+    #[serde] anchors: Vec<(usize, usize)>,
+) -> bool {
+    if let Some(runtime_state) = state.try_borrow::<Rc<RefCell<TsRuntimeState>>>() {
+        let runtime_state = runtime_state.borrow();
+        let result = runtime_state
+            .command_sender
+            .send(PluginCommand::SetScrollSyncAnchors { group_id, anchors });
+        return result.is_ok();
+    }
+    false
+}
+
+/// Remove a scroll sync group
+#[op2(fast)]
+fn op_fresh_remove_scroll_sync_group(state: &mut OpState, group_id: u32) -> bool {
+    if let Some(runtime_state) = state.try_borrow::<Rc<RefCell<TsRuntimeState>>>() {
+        let runtime_state = runtime_state.borrow();
+        let result = runtime_state
+            .command_sender
+            .send(PluginCommand::RemoveScrollSyncGroup { group_id });
+        return result.is_ok();
+    }
+    false
+}
+
+/// Compare two open buffers side by side, as "Compare Buffer With…" does
+///
+/// The left buffer is made active and the right one shown in a new split.
+/// The diff follows edits to either buffer until one of them is closed.
+/// @param left_buffer_id - Buffer shown on the left
+/// @param right_buffer_id - Buffer shown on the right
+/// @returns true if the command was sent
 #[op2(fast)]
-fn op_fresh_disable_lsp_for_language(state: &mut OpState, #[string] language: String) -> bool {
+fn op_fresh_compare_buffers(
+    state: &mut OpState,
+    left_buffer_id: u32,
+    right_buffer_id: u32,
+) -> bool {
     if let Some(runtime_state) = state.try_borrow::<Rc<RefCell<TsRuntimeState>>>() {
         let runtime_state = runtime_state.borrow();
         let result = runtime_state
             .command_sender
-            .send(PluginCommand::DisableLspForLanguage { language });
+            .send(PluginCommand::CompareBuffers {
+                left: BufferId(left_buffer_id as usize),
+                right: BufferId(right_buffer_id as usize),
+            });
         return result.is_ok();
     }
-    false
+    false
+}
+
+/// Watch a file or glob for changes on disk
+///
+/// The handler is called with `{watch_id, path, kind}` where kind is
+/// "created", "modified" or "removed". Rapid successive writes to a file
+/// produce one event. Relative paths are taken from the working directory.
+/// Globs such as "logs/*.txt" are matched under their leading directory, with
+/// "**" matching any number of directories.
+/// Throws if the plugin already has the maximum number of watches.
+/// @param path_or_glob - File path or glob to watch
+/// @param handler_name - Name of globalThis function to call with each change
+/// @returns Watch ID for unwatchPath
+/// @example
+/// globalThis.onTagsChanged = (change) => {
+///   editor.setStatus(`${change.path} ${change.kind}`);
+/// };
+/// const watchId = editor.watchPath("tags", "onTagsChanged");
+#[op2(fast)]
+fn op_fresh_watch_path(
+    state: &mut OpState,
+    #[string] path_or_glob: String,
+    #[string] handler_name: String,
+    #[string] plugin_name: String,
+) -> Result<u32, JsErrorBox> {
+    let Some(runtime_state) = state.try_borrow::<Rc<RefCell<TsRuntimeState>>>() else {
+        return Err(JsErrorBox::generic("Plugin runtime is not available"));
+    };
+    let runtime_state = runtime_state.borrow();
+
+    let mut watches = runtime_state.path_watches.borrow_mut();
+    let active = watches.values().filter(|p| **p == plugin_name).count();
+    if active >= MAX_PATH_WATCHES_PER_PLUGIN {
+        return Err(JsErrorBox::generic(format!(
+            "Cannot watch '{}': plugin '{}' already has {} path watches (the maximum); call unwatchPath first",
+            path_or_glob, plugin_name, MAX_PATH_WATCHES_PER_PLUGIN
+        )));
+    }
+
+    let watch_id = {
+        let mut next = runtime_state.next_watch_id.borrow_mut();
+        let id = *next;
+        *next += 1;
+        id
+    };
+    watches.insert(watch_id, plugin_name.clone());
+    runtime_state.event_handlers.borrow_mut().insert(
+        path_watch_hook_name(watch_id),
+        vec![EventHandlerInfo {
+            handler: handler_name,
+            plugin: plugin_name.clone(),
+        }],
+    );
+
+    let _ = runtime_state.command_sender.send(PluginCommand::WatchPath {
+        watch_id,
+        plugin: plugin_name,
+        pattern: path_or_glob,
+    });
+    Ok(watch_id)
+}
+
+/// Stop a watch started with watchPath
+/// @param watch_id - ID returned by watchPath
+/// @returns true if the watch existed
+#[op2(fast)]
+fn op_fresh_unwatch_path(state: &mut OpState, watch_id: u32) -> bool {
+    if let Some(runtime_state) = state.try_borrow::<Rc<RefCell<TsRuntimeState>>>() {
+        return runtime_state.borrow().remove_path_watch(watch_id);
+    }
+    false
+}
+
+impl TsRuntimeState {
+    /// Forget a gutter column and tell the editor to remove it
+    fn remove_gutter_column(&self, name: &str) -> bool {
+        if self.gutter_columns.borrow_mut().remove(name).is_none() {
+            return false;
+        }
+        let _ = self
+            .command_sender
+            .send(PluginCommand::UnregisterGutterColumn {
+                name: name.to_string(),
+            });
+        true
+    }
+
+    /// Forget a file explorer action and tell the editor to remove its entry
+    fn remove_explorer_action(&self, id: &str) -> bool {
+        if self.explorer_actions.borrow_mut().remove(id).is_none() {
+            return false;
+        }
+        self.event_handlers
+            .borrow_mut()
+            .remove(&explorer_action_hook_name(id));
+        let _ = self
+            .command_sender
+            .send(PluginCommand::UnregisterExplorerAction { id: id.to_string() });
+        true
+    }
+
+    /// Forget a path watch and tell the editor to stop polling it
+    fn remove_path_watch(&self, watch_id: u32) -> bool {
+        if self.path_watches.borrow_mut().remove(&watch_id).is_none() {
+            return false;
+        }
+        self.event_handlers
+            .borrow_mut()
+            .remove(&path_watch_hook_name(watch_id));
+        let _ = self
+            .command_sender
+            .send(PluginCommand::UnwatchPath { watch_id });
+        true
+    }
+
+    /// Forget a progress entry and tell the editor to remove it
+    fn remove_progress(&self, progress_id: u32, abandoned: bool) -> bool {
+        if self.progress.borrow_mut().remove(&progress_id).is_none() {
+            return false;
+        }
+        self.event_handlers
+            .borrow_mut()
+            .remove(&progress_cancel_hook_name(progress_id));
+        let _ = self.command_sender.send(PluginCommand::FinishProgress {
+            progress_id,
+            abandoned,
+        });
+        true
+    }
 }
 
-/// Create a scroll sync group for anchor-based synchronized scrolling
+/// Options for progressStart
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct TsProgressOptions {
+    /// What is in progress, e.g. "Indexing workspace"
+    pub title: String,
+    /// Whether the user may cancel it (default false)
+    pub cancellable: Option<bool>,
+}
+
+/// Show the progress of a long operation
 ///
-/// Used for side-by-side diff views where two panes need to scroll together.
-/// The plugin provides the group ID (must be unique per plugin).
-#[op2(fast)]
-fn op_fresh_create_scroll_sync_group(
+/// Prefer `startProgress`, which wraps these ops in a handle. A single entry
+/// shows in the status bar, several show as notifications. The entry starts
+/// with a spinner until a percentage is given, and is finished for the plugin
+/// with a warning if the plugin is unloaded or stops updating it for a minute.
+/// @param options - Title and whether the user may cancel it
+/// @returns Progress ID for progressUpdate, progressFinish and progressOnCancel
+#[op2]
+fn op_fresh_progress_start(
     state: &mut OpState,
-    group_id: u32,
-    left_split: u32,
-    right_split: u32,
-) -> bool {
-    use crate::model::event::SplitId;
-
+    #[serde] options: TsProgressOptions,
+    #[string] plugin_name: String,
+) -> u32 {
+    let progress_id = crate::view::progress::next_progress_id();
     if let Some(runtime_state) = state.try_borrow::<Rc<RefCell<TsRuntimeState>>>() {
         let runtime_state = runtime_state.borrow();
-        let result = runtime_state
+        runtime_state
+            .progress
+            .borrow_mut()
+            .insert(progress_id, plugin_name.clone());
+        let _ = runtime_state
             .command_sender
-            .send(PluginCommand::CreateScrollSyncGroup {
-                group_id,
-                left_split: SplitId(left_split as usize),
-                right_split: SplitId(right_split as usize),
+            .send(PluginCommand::StartProgress {
+                progress_id,
+                plugin: plugin_name,
+                title: options.title,
+                cancellable: options.cancellable.unwrap_or(false),
             });
-        return result.is_ok();
     }
-    false
+    progress_id
 }
 
-/// Set sync anchors for a scroll sync group
-///
-/// Anchors map corresponding line numbers between left and right buffers.
-/// Each anchor is a tuple of (left_line, right_line).
+/// Report progress: a number sets the percentage (0-100), a string the message
+/// @param progress_id - ID returned by progressStart
+/// @param percent_or_message - Percentage or message
+/// @returns false if the progress was already finished
 #[op2]
-fn op_fresh_set_scroll_sync_anchors(
+fn op_fresh_progress_update(
     state: &mut OpState,
-    group_id: u32,
-    #[serde] anchors: Vec<(usize, usize)>,
+    progress_id: u32,
+    #[serde] percent_or_message: serde_json::Value,
 ) -> bool {
+    let (percentage, message) = match percent_or_message {
+        serde_json::Value::Number(n) => match n.as_f64() {
+            Some(percent) => (Some(percent.round().clamp(0.0, 100.0) as u8), None),
+            None => return false,
+        },
+        serde_json::Value::String(message) => (None, Some(message)),
+        _ => return false,
+    };
     if let Some(runtime_state) = state.try_borrow::<Rc<RefCell<TsRuntimeState>>>() {
         let runtime_state = runtime_state.borrow();
-        let result = runtime_state
+        if !runtime_state.progress.borrow().contains_key(&progress_id) {
+            return false;
+        }
+        let _ = runtime_state
             .command_sender
-            .send(PluginCommand::SetScrollSyncAnchors { group_id, anchors });
-        return result.is_ok();
+            .send(PluginCommand::UpdateProgress {
+                progress_id,
+                percentage,
+                message,
+            });
+        return true;
     }
     false
 }
 
-/// Remove a scroll sync group
+/// Remove a progress entry
+/// @param progress_id - ID returned by progressStart
+/// @returns false if it was already finished
 #[op2(fast)]
-fn op_fresh_remove_scroll_sync_group(state: &mut OpState, group_id: u32) -> bool {
+fn op_fresh_progress_finish(state: &mut OpState, progress_id: u32) -> bool {
     if let Some(runtime_state) = state.try_borrow::<Rc<RefCell<TsRuntimeState>>>() {
-        let runtime_state = runtime_state.borrow();
-        let result = runtime_state
-            .command_sender
-            .send(PluginCommand::RemoveScrollSyncGroup { group_id });
-        return result.is_ok();
+        return runtime_state.borrow().remove_progress(progress_id, false);
     }
     false
 }
 
+/// Call a handler when the user cancels a progress entry
+///
+/// The handler is called once with `{progress_id}`. The entry shows as
+/// cancelling until the plugin finishes it.
+/// @param progress_id - ID returned by progressStart
+/// @param handler_name - Name of globalThis function to call
+/// @returns false if the progress was already finished
+#[op2(fast)]
+fn op_fresh_progress_on_cancel(
+    state: &mut OpState,
+    progress_id: u32,
+    #[string] handler_name: String,
+    #[string] plugin_name: String,
+) -> bool {
+    let Some(runtime_state) = state.try_borrow::<Rc<RefCell<TsRuntimeState>>>() else {
+        return false;
+    };
+    let runtime_state = runtime_state.borrow();
+    if !runtime_state.progress.borrow().contains_key(&progress_id) {
+        return false;
+    }
+    runtime_state.event_handlers.borrow_mut().insert(
+        progress_cancel_hook_name(progress_id),
+        vec![EventHandlerInfo {
+            handler: handler_name,
+            plugin: plugin_name,
+        }],
+    );
+    true
+}
+
 // Define the extension with our ops
 extension!(
     fresh_runtime,
@@ -3401,6 +5650,8 @@ extension!(
         op_fresh_reload_config,
         op_fresh_get_config,
         op_fresh_get_user_config,
+        op_fresh_is_project_search_case_sensitive,
+        op_fresh_get_theme_colors,
         op_fresh_get_current_locale,
         op_fresh_plugin_translate,
         op_fresh_error,
@@ -3418,11 +5669,16 @@ extension!(
         op_fresh_is_buffer_modified,
         op_fresh_insert_text,
         op_fresh_delete_range,
+        op_fresh_apply_edits,
+        op_fresh_finish_save_action,
         op_fresh_add_overlay,
         op_fresh_remove_overlay,
         op_fresh_clear_namespace,
+        op_fresh_set_revert_stable_namespace,
         op_fresh_clear_overlays_in_range,
         op_fresh_set_line_numbers,
+        op_fresh_set_buffer_idle_close,
+        op_fresh_set_buffer_pinned,
         op_fresh_clear_all_overlays,
         op_fresh_add_virtual_text,
         op_fresh_remove_virtual_text,
@@ -3435,6 +5691,10 @@ extension!(
         op_fresh_refresh_lines,
         op_fresh_set_line_indicator,
         op_fresh_clear_line_indicators,
+        op_fresh_register_gutter_column,
+        op_fresh_unregister_gutter_column,
+        op_fresh_register_explorer_action,
+        op_fresh_unregister_explorer_action,
         op_fresh_insert_at_cursor,
         op_fresh_register_command,
         op_fresh_unregister_command,
@@ -3453,31 +5713,44 @@ extension!(
         op_fresh_get_buffer_info,
         op_fresh_list_buffers,
         op_fresh_get_all_diagnostics,
+        op_fresh_get_diagnostics_summary,
         op_fresh_get_primary_cursor,
         op_fresh_get_all_cursors,
         op_fresh_get_viewport,
+        op_fresh_get_terminal_info,
         op_fresh_start_prompt,
         op_fresh_start_prompt_with_initial,
         op_fresh_set_prompt_suggestions,
+        op_fresh_get_prompt_state,
+        op_fresh_close_prompt,
+        op_fresh_accept_prompt_suggestion,
+        op_fresh_get_file_explorer_state,
+        op_fresh_reveal_in_file_explorer,
+        op_fresh_set_file_explorer_root,
         op_fresh_read_file,
         op_fresh_write_file,
         op_fresh_file_exists,
         op_fresh_file_stat,
         op_fresh_get_env,
         op_fresh_get_cwd,
+        op_fresh_get_recent_projects,
         op_fresh_path_join,
         op_fresh_path_dirname,
         op_fresh_path_basename,
         op_fresh_path_extname,
         op_fresh_path_is_absolute,
         op_fresh_read_dir,
+        op_fresh_compare_directories,
         op_fresh_on,
         op_fresh_off,
         op_fresh_get_handlers,
+        op_fresh_take_event,
         // Virtual buffer operations
         op_fresh_create_virtual_buffer_in_split,
         op_fresh_create_virtual_buffer_in_existing_split,
         op_fresh_create_virtual_buffer,
+        op_fresh_create_buffer,
+        op_fresh_create_buffer_from_file,
         op_fresh_send_lsp_request,
         op_fresh_define_mode,
         op_fresh_show_buffer,
@@ -3487,23 +5760,47 @@ extension!(
         op_fresh_close_split,
         op_fresh_focus_split,
         op_fresh_set_split_ratio,
+        op_fresh_set_split_locked,
+        op_fresh_is_split_locked,
+        op_fresh_set_winbar_segment,
         op_fresh_distribute_splits_evenly,
         op_fresh_set_buffer_cursor,
+        op_fresh_goto_byte,
         op_fresh_get_text_properties_at_cursor,
         op_fresh_set_virtual_buffer_content,
+        op_fresh_append_virtual_buffer_content,
         // Vi mode support operations
         op_fresh_execute_action,
         op_fresh_execute_actions,
+        op_fresh_execute_action_await,
+        op_fresh_execute_actions_await,
+        op_fresh_get_keymap,
         op_fresh_get_buffer_text,
+        op_fresh_byte_to_line_col,
+        op_fresh_line_col_to_byte,
         op_fresh_set_editor_mode,
         op_fresh_get_editor_mode,
+        op_fresh_set_cursor_style,
         // LSP helper operations
         op_fresh_show_action_popup,
+        op_fresh_notify,
+        op_fresh_show_anchored_popup,
+        op_fresh_close_anchored_popup,
+        op_fresh_review_pending_edits,
+        op_fresh_set_config_value,
         op_fresh_disable_lsp_for_language,
         // Scroll sync operations
         op_fresh_create_scroll_sync_group,
         op_fresh_set_scroll_sync_anchors,
         op_fresh_remove_scroll_sync_group,
+        op_fresh_compare_buffers,
+        op_fresh_watch_path,
+        op_fresh_read_buffer_chunks,
+        op_fresh_unwatch_path,
+        op_fresh_progress_start,
+        op_fresh_progress_update,
+        op_fresh_progress_finish,
+        op_fresh_progress_on_cancel,
     ],
 );
 
@@ -3518,9 +5815,15 @@ pub type PendingResponses = Arc<
 pub struct TypeScriptRuntime {
     js_runtime: JsRuntime,
     /// Shared event handlers registry
-    event_handlers: Rc<RefCell<HashMap<String, Vec<String>>>>,
+    event_handlers: Rc<RefCell<HashMap<String, Vec<EventHandlerInfo>>>>,
     /// Pending response senders (shared with runtime state for delivering responses)
     pending_responses: PendingResponses,
+    /// Shared state of the ops, for cleaning up after unloaded plugins
+    runtime_state: Rc<RefCell<TsRuntimeState>>,
+    /// Times each module path has been loaded, so a reload evaluates it again
+    module_loads: HashMap<String, u32>,
+    /// Transpiler shared with the module loader
+    transpiler: Rc<Transpiler>,
 }
 
 impl TypeScriptRuntime {
@@ -3549,7 +5852,7 @@ impl TypeScriptRuntime {
     ) -> Result<Self> {
         tracing::debug!("TypeScriptRuntime::with_state_and_responses: initializing V8 platform");
         // Initialize V8 platform before creating JsRuntime
-        crate::v8_init::init();
+        crate::v8_init::init().map_err(|e| anyhow!("V8 platform initialization failed: {}", e))?;
         tracing::debug!("TypeScriptRuntime::with_state_and_responses: V8 platform initialized");
 
         tracing::debug!("TypeScriptRuntime::with_state_and_responses: creating runtime state");
@@ -3564,14 +5867,24 @@ impl TypeScriptRuntime {
             cancellable_processes: Rc::new(RefCell::new(HashMap::new())),
             process_pids: Rc::new(RefCell::new(HashMap::new())),
             next_process_id: Rc::new(RefCell::new(1)),
+            path_watches: Rc::new(RefCell::new(HashMap::new())),
+            next_watch_id: Rc::new(RefCell::new(1)),
+            progress: Rc::new(RefCell::new(HashMap::new())),
+            gutter_columns: Rc::new(RefCell::new(HashMap::new())),
+            explorer_actions: Rc::new(RefCell::new(HashMap::new())),
+            next_read_id: Rc::new(RefCell::new(1)),
+            pending_event: None,
         }));
 
         tracing::debug!(
             "TypeScriptRuntime::with_state_and_responses: creating JsRuntime with deno_core"
         );
         let js_runtime_start = std::time::Instant::now();
+        let transpiler = Rc::new(Transpiler::default());
         let mut js_runtime = JsRuntime::new(RuntimeOptions {
-            module_loader: Some(Rc::new(TypeScriptModuleLoader)),
+            module_loader: Some(Rc::new(TypeScriptModuleLoader {
+                transpiler: Rc::clone(&transpiler),
+            })),
             extensions: vec![fresh_runtime::init()],
             ..Default::default()
         });
@@ -3581,7 +5894,10 @@ impl TypeScriptRuntime {
         );
 
         // Store the runtime state in the op state
-        js_runtime.op_state().borrow_mut().put(runtime_state);
+        js_runtime
+            .op_state()
+            .borrow_mut()
+            .put(Rc::clone(&runtime_state));
 
         // Set up the global editor API
         js_runtime
@@ -3613,6 +5929,12 @@ impl TypeScriptRuntime {
                     getUserConfig() {
                         return core.ops.op_fresh_get_user_config();
                     },
+                    isProjectSearchCaseSensitive(pattern, isRegex) {
+                        return core.ops.op_fresh_is_project_search_case_sensitive(pattern, isRegex);
+                    },
+                    getThemeColors() {
+                        return core.ops.op_fresh_get_theme_colors();
+                    },
 
                     // Clipboard
                     copyToClipboard(text) {
@@ -3649,10 +5971,16 @@ impl TypeScriptRuntime {
                     deleteRange(bufferId, start, end) {
                         return core.ops.op_fresh_delete_range(bufferId, start, end);
                     },
+                    applyEdits(bufferId, edits, label = null) {
+                        return core.ops.op_fresh_apply_edits(bufferId, edits, label);
+                    },
+                    finishSaveAction(requestId, error = null) {
+                        return core.ops.op_fresh_finish_save_action(requestId, error);
+                    },
 
                     // Overlays
-                    addOverlay(bufferId, namespace, start, end, r, g, b, underline, bold = false, italic = false, bg_r = -1, bg_g = -1, bg_b = -1, extend_to_line_end = false) {
-                        return core.ops.op_fresh_add_overlay(bufferId, namespace, start, end, r, g, b, bg_r, bg_g, bg_b, underline, bold, italic, extend_to_line_end);
+                    addOverlay(bufferId, namespace, start, end, r, g, b, underline, bold = false, italic = false, bg_r = -1, bg_g = -1, bg_b = -1, extend_to_line_end = false, colorName = "", bgColorName = "") {
+                        return core.ops.op_fresh_add_overlay(bufferId, namespace, start, end, r, g, b, bg_r, bg_g, bg_b, underline, bold, italic, extend_to_line_end, colorName, bgColorName);
                     },
                     removeOverlay(bufferId, handle) {
                         return core.ops.op_fresh_remove_overlay(bufferId, handle);
@@ -3660,6 +5988,9 @@ impl TypeScriptRuntime {
                     clearNamespace(bufferId, namespace) {
                         return core.ops.op_fresh_clear_namespace(bufferId, namespace);
                     },
+                    setRevertStableNamespace(namespace, stable) {
+                        return core.ops.op_fresh_set_revert_stable_namespace(namespace, stable);
+                    },
                     clearOverlaysInRange(bufferId, start, end) {
                         return core.ops.op_fresh_clear_overlays_in_range(bufferId, start, end);
                     },
@@ -3671,10 +6002,16 @@ impl TypeScriptRuntime {
                     setLineNumbers(bufferId, enabled) {
                         return core.ops.op_fresh_set_line_numbers(bufferId, enabled);
                     },
+                    setBufferIdleClose(bufferId, enabled) {
+                        return core.ops.op_fresh_set_buffer_idle_close(bufferId, enabled);
+                    },
+                    setBufferPinned(bufferId, pinned) {
+                        return core.ops.op_fresh_set_buffer_pinned(bufferId, pinned);
+                    },
 
                     // Virtual text
-                    addVirtualText(bufferId, virtualTextId, position, text, r, g, b, before, useBg = false) {
-                        return core.ops.op_fresh_add_virtual_text(bufferId, virtualTextId, position, text, r, g, b, before, useBg);
+                    addVirtualText(bufferId, virtualTextId, position, text, r, g, b, before, useBg = false, colorName = "") {
+                        return core.ops.op_fresh_add_virtual_text(bufferId, virtualTextId, position, text, r, g, b, before, useBg, colorName);
                     },
                     removeVirtualText(bufferId, virtualTextId) {
                         return core.ops.op_fresh_remove_virtual_text(bufferId, virtualTextId);
@@ -3687,8 +6024,8 @@ impl TypeScriptRuntime {
                     },
 
                     // Virtual lines
-                    addVirtualLine(bufferId, position, text, fgR, fgG, fgB, bgR, bgG, bgB, above, namespace, priority = 0) {
-                        return core.ops.op_fresh_add_virtual_line(bufferId, position, text, fgR, fgG, fgB, bgR, bgG, bgB, above, namespace, priority);
+                    addVirtualLine(bufferId, position, text, fgR, fgG, fgB, bgR, bgG, bgB, above, namespace, priority = 0, fgColorName = "", bgColorName = "") {
+                        return core.ops.op_fresh_add_virtual_line(bufferId, position, text, fgR, fgG, fgB, bgR, bgG, bgB, above, namespace, priority, fgColorName, bgColorName);
                     },
                     clearVirtualTextNamespace(bufferId, namespace) {
                         return core.ops.op_fresh_clear_virtual_text_namespace(bufferId, namespace);
@@ -3707,8 +6044,8 @@ impl TypeScriptRuntime {
                     },
 
                     // Line indicators
-                    setLineIndicator(bufferId, line, namespace, symbol, r, g, b, priority) {
-                        return core.ops.op_fresh_set_line_indicator(bufferId, line, namespace, symbol, r, g, b, priority);
+                    setLineIndicator(bufferId, line, namespace, symbol, r, g, b, priority, colorName = "", column = "") {
+                        return core.ops.op_fresh_set_line_indicator(bufferId, line, namespace, symbol, r, g, b, priority, colorName, column);
                     },
                     clearLineIndicators(bufferId, namespace) {
                         return core.ops.op_fresh_clear_line_indicators(bufferId, namespace);
@@ -3753,6 +6090,9 @@ impl TypeScriptRuntime {
                     getAllDiagnostics() {
                         return core.ops.op_fresh_get_all_diagnostics();
                     },
+                    getDiagnosticsSummary() {
+                        return core.ops.op_fresh_get_diagnostics_summary();
+                    },
                     getPrimaryCursor() {
                         return core.ops.op_fresh_get_primary_cursor();
                     },
@@ -3762,6 +6102,9 @@ impl TypeScriptRuntime {
                     getViewport() {
                         return core.ops.op_fresh_get_viewport();
                     },
+                    getTerminalInfo() {
+                        return core.ops.op_fresh_get_terminal_info();
+                    },
 
                     startPrompt(label, promptType) {
                         return core.ops.op_fresh_start_prompt(label, promptType);
@@ -3772,6 +6115,24 @@ impl TypeScriptRuntime {
                     setPromptSuggestions(suggestions) {
                         return core.ops.op_fresh_set_prompt_suggestions(suggestions);
                     },
+                    getPromptState() {
+                        return core.ops.op_fresh_get_prompt_state();
+                    },
+                    closePrompt(promptType = null) {
+                        return core.ops.op_fresh_close_prompt(promptType);
+                    },
+                    acceptPromptSuggestion(index) {
+                        return core.ops.op_fresh_accept_prompt_suggestion(index);
+                    },
+                    getFileExplorerState() {
+                        return core.ops.op_fresh_get_file_explorer_state();
+                    },
+                    revealInFileExplorer(path) {
+                        return core.ops.op_fresh_reveal_in_file_explorer(path);
+                    },
+                    setFileExplorerRoot(path) {
+                        return core.ops.op_fresh_set_file_explorer_root(path);
+                    },
 
                     spawnProcess(command, args = [], cwd = null) {
                         const effectiveCwd = cwd ?? core.ops.op_fresh_get_cwd();
@@ -3828,6 +6189,9 @@ impl TypeScriptRuntime {
                     getCwd() {
                         return core.ops.op_fresh_get_cwd();
                     },
+                    getRecentProjects() {
+                        return core.ops.op_fresh_get_recent_projects();
+                    },
 
                     pathJoin(...parts) {
                         return core.ops.op_fresh_path_join(parts);
@@ -3847,13 +6211,10 @@ impl TypeScriptRuntime {
                     readDir(path) {
                         return core.ops.op_fresh_read_dir(path);
                     },
-
-                    on(eventName, handlerName) {
-                        return core.ops.op_fresh_on(eventName, handlerName);
-                    },
-                    off(eventName, handlerName) {
-                        return core.ops.op_fresh_off(eventName, handlerName);
+                    compareDirectories(left, right) {
+                        return core.ops.op_fresh_compare_directories(left, right);
                     },
+
                     getHandlers(eventName) {
                         return core.ops.op_fresh_get_handlers(eventName);
                     },
@@ -3867,9 +6228,15 @@ impl TypeScriptRuntime {
                     createVirtualBuffer(options) {
                         return core.ops.op_fresh_create_virtual_buffer(options);
                     },
+                    createBuffer(options = null) {
+                        return core.ops.op_fresh_create_buffer(options);
+                    },
+                    createBufferFromFile(path, options = null) {
+                        return core.ops.op_fresh_create_buffer_from_file(path, options);
+                    },
                     defineMode(name, parent, bindings, readOnly = false) {
                         const parentStr = parent != null ? parent : "";
-                        return core.ops.op_fresh_define_mode(name, parentStr, bindings, readOnly);
+                        return core.ops.op_fresh_define_mode(name, parentStr, bindings, readOnly, "");
                     },
                     showBuffer(bufferId) {
                         return core.ops.op_fresh_show_buffer(bufferId);
@@ -3889,6 +6256,15 @@ impl TypeScriptRuntime {
                     setSplitRatio(splitId, ratio) {
                         return core.ops.op_fresh_set_split_ratio(splitId, ratio);
                     },
+                    setSplitLocked(splitId, locked) {
+                        return core.ops.op_fresh_set_split_locked(splitId, locked);
+                    },
+                    isSplitLocked(splitId) {
+                        return core.ops.op_fresh_is_split_locked(splitId);
+                    },
+                    setWinbarSegment(splitId, id, text, priority = 0, r = -1, g = -1, b = -1, colorName = "") {
+                        return core.ops.op_fresh_set_winbar_segment(splitId, id, text, priority, r, g, b, colorName);
+                    },
                     setSplitScroll(splitId, topByte) {
                         return core.ops.op_fresh_set_split_scroll(splitId, topByte);
                     },
@@ -3898,12 +6274,18 @@ impl TypeScriptRuntime {
                     setBufferCursor(bufferId, position) {
                         return core.ops.op_fresh_set_buffer_cursor(bufferId, position);
                     },
+                    gotoByte(bufferId, offset) {
+                        return core.ops.op_fresh_goto_byte(bufferId, offset);
+                    },
                     getTextPropertiesAtCursor(bufferId) {
                         return core.ops.op_fresh_get_text_properties_at_cursor(bufferId);
                     },
                     setVirtualBufferContent(bufferId, entries) {
                         return core.ops.op_fresh_set_virtual_buffer_content(bufferId, entries);
                     },
+                    appendVirtualBufferContent(bufferId, entries) {
+                        return core.ops.op_fresh_append_virtual_buffer_content(bufferId, entries);
+                    },
 
                     executeAction(actionName) {
                         return core.ops.op_fresh_execute_action(actionName);
@@ -3911,8 +6293,14 @@ impl TypeScriptRuntime {
                     executeActions(actions) {
                         return core.ops.op_fresh_execute_actions(actions);
                     },
-                    getBufferText(bufferId, start, end) {
-                        return core.ops.op_fresh_get_buffer_text(bufferId, start, end);
+                    executeActionAwait(actionName) {
+                        return core.ops.op_fresh_execute_action_await(actionName);
+                    },
+                    executeActionsAwait(actions) {
+                        return core.ops.op_fresh_execute_actions_await(actions);
+                    },
+                    getKeymap(context) {
+                        return core.ops.op_fresh_get_keymap(context != null ? context : "");
                     },
                     setEditorMode(mode) {
                         return core.ops.op_fresh_set_editor_mode(mode);
@@ -3920,12 +6308,30 @@ impl TypeScriptRuntime {
                     getEditorMode() {
                         return core.ops.op_fresh_get_editor_mode();
                     },
+                    setCursorStyle(style) {
+                        return core.ops.op_fresh_set_cursor_style(style);
+                    },
 
                     showActionPopup(options) {
                         return core.ops.op_fresh_show_action_popup(options);
                     },
-                    disableLspForLanguage(language) {
-                        return core.ops.op_fresh_disable_lsp_for_language(language);
+                    notify(options) {
+                        return core.ops.op_fresh_notify(options);
+                    },
+                    showAnchoredPopup(options) {
+                        return core.ops.op_fresh_show_anchored_popup(options);
+                    },
+                    closeAnchoredPopup() {
+                        return core.ops.op_fresh_close_anchored_popup();
+                    },
+                    reviewPendingEdits(options) {
+                        return core.ops.op_fresh_review_pending_edits(options);
+                    },
+                    setConfigValue(path, value) {
+                        return core.ops.op_fresh_set_config_value(path, value);
+                    },
+                    disableLspForLanguage(language, server = null) {
+                        return core.ops.op_fresh_disable_lsp_for_language(language, server);
                     },
 
                     createScrollSyncGroup(groupId, leftSplit, rightSplit) {
@@ -3937,6 +6343,18 @@ impl TypeScriptRuntime {
                     removeScrollSyncGroup(groupId) {
                         return core.ops.op_fresh_remove_scroll_sync_group(groupId);
                     },
+                    compareBuffers(leftBufferId, rightBufferId) {
+                        return core.ops.op_fresh_compare_buffers(leftBufferId, rightBufferId);
+                    },
+                    unwatchPath(watchId) {
+                        return core.ops.op_fresh_unwatch_path(watchId);
+                    },
+                    progressUpdate(progressId, percentOrMessage) {
+                        return core.ops.op_fresh_progress_update(progressId, percentOrMessage);
+                    },
+                    progressFinish(progressId) {
+                        return core.ops.op_fresh_progress_finish(progressId);
+                    },
 
                     getCurrentLocale() {
                         return core.ops.op_fresh_get_current_locale();
@@ -3968,8 +6386,85 @@ impl TypeScriptRuntime {
                         },
 
                         // Plugin-specific command registration
-                        registerCommand(name, description, action, contexts = "") {
-                            return core.ops.op_fresh_register_command(name, description, action, contexts, pluginName);
+                        registerCommand(name, description, action, contexts = "", options = {}) {
+                            return core.ops.op_fresh_register_command(name, description, action, contexts, pluginName, options);
+                        },
+
+                        // Panels remember their plugin so it can restore them with the session
+                        createVirtualBufferInSplit(options) {
+                            return core.ops.op_fresh_create_virtual_buffer_in_split({ ...options, plugin: pluginName });
+                        },
+
+                        // Mode bindings list their plugin as the source in the keymap
+                        defineMode(name, parent, bindings, readOnly = false) {
+                            const parentStr = parent != null ? parent : "";
+                            return core.ops.op_fresh_define_mode(name, parentStr, bindings, readOnly, pluginName);
+                        },
+
+                        // Event handlers are removed when their plugin unloads
+                        on(eventName, handlerName) {
+                            return core.ops.op_fresh_on(eventName, handlerName, pluginName);
+                        },
+                        off(eventName, handlerName) {
+                            return core.ops.op_fresh_off(eventName, handlerName, pluginName);
+                        },
+
+                        // Watches count against their plugin's limit and end when it unloads
+                        watchPath(pathOrGlob, handlerName) {
+                            return core.ops.op_fresh_watch_path(pathOrGlob, handlerName, pluginName);
+                        },
+
+                        // Gutter columns go away when their plugin unloads
+                        registerGutterColumn(name, width, alignment = "left") {
+                            return core.ops.op_fresh_register_gutter_column(name, width, alignment, pluginName);
+                        },
+                        unregisterGutterColumn(name) {
+                            return core.ops.op_fresh_unregister_gutter_column(name, pluginName);
+                        },
+
+                        // Explorer context menu entries go away when their plugin unloads
+                        registerExplorerAction(options) {
+                            return core.ops.op_fresh_register_explorer_action(options, pluginName);
+                        },
+                        unregisterExplorerAction(id) {
+                            return core.ops.op_fresh_unregister_explorer_action(id, pluginName);
+                        },
+
+                        // Progress entries end when their plugin unloads
+                        progressStart(options) {
+                            return core.ops.op_fresh_progress_start(options, pluginName);
+                        },
+                        progressOnCancel(progressId, handlerName) {
+                            return core.ops.op_fresh_progress_on_cancel(progressId, handlerName, pluginName);
+                        },
+                        async startProgress(options) {
+                            const id = core.ops.op_fresh_progress_start(options, pluginName);
+                            return {
+                                id,
+                                update(percentOrMessage) {
+                                    return core.ops.op_fresh_progress_update(id, percentOrMessage);
+                                },
+                                finish() {
+                                    return core.ops.op_fresh_progress_finish(id);
+                                },
+                                onCancel(handlerName) {
+                                    return core.ops.op_fresh_progress_on_cancel(id, handlerName, pluginName);
+                                },
+                            };
+                        },
+
+                        // Buffer text read by a plugin is counted against its transfer budget
+                        getBufferText(bufferId, start, end) {
+                            return core.ops.op_fresh_get_buffer_text(bufferId, start, end, pluginName);
+                        },
+                        byteToLineCol(bufferId, byte) {
+                            return core.ops.op_fresh_byte_to_line_col(bufferId, byte);
+                        },
+                        lineColToByte(bufferId, line, column) {
+                            return core.ops.op_fresh_line_col_to_byte(bufferId, line, column);
+                        },
+                        readBufferChunks(bufferId, chunkSize, handlerName) {
+                            return core.ops.op_fresh_read_buffer_chunks(bufferId, chunkSize, handlerName, pluginName);
                         },
 
                         // Plugin-specific translation
@@ -4009,17 +6504,28 @@ impl TypeScriptRuntime {
                 };
 
                 // Pre-compiled event dispatcher for performance
-                globalThis.__eventDispatcher = async function(handlerName, eventData) {
+                globalThis.__eventDispatcher = async function(handlerName, pluginName, eventData) {
                     const handler = globalThis[handlerName];
                     if (typeof handler === 'function') {
                         const result = handler(eventData);
                         const finalResult = (result instanceof Promise) ? await result : result;
                         return finalResult !== false;
                     } else {
-                        defaultEditor.debug('Event handler "' + handlerName + '" is not defined');
+                        defaultEditor.warn('Event handler "' + handlerName + '" of plugin "' + pluginName + '" is not defined');
                         return true;
                     }
                 };
+
+                // Calls each handler of the pending event in registration
+                // order, each with its own copy of the event data
+                globalThis.__dispatchEvent = function() {
+                    const event = core.ops.op_fresh_take_event();
+                    if (!event) return;
+                    const json = JSON.stringify(event.data);
+                    for (const { handler, plugin } of event.handlers) {
+                        globalThis.__eventDispatcher(handler, plugin, JSON.parse(json));
+                    }
+                };
                 "#
                 .to_string(),
             )
@@ -4033,9 +6539,88 @@ impl TypeScriptRuntime {
             js_runtime,
             event_handlers,
             pending_responses,
+            runtime_state,
+            module_loads: HashMap::new(),
+            transpiler,
         })
     }
 
+    /// Remove the event handlers of an unloaded plugin
+    pub fn remove_plugin_handlers(&self, plugin_name: &str) {
+        let mut handlers = self.event_handlers.borrow_mut();
+        for handler_list in handlers.values_mut() {
+            handler_list.retain(|h| h.plugin != plugin_name);
+        }
+        handlers.retain(|_, handler_list| !handler_list.is_empty());
+    }
+
+    /// Stop the path watches of an unloaded plugin
+    pub fn unwatch_plugin_paths(&self, plugin_name: &str) {
+        let state = self.runtime_state.borrow();
+        let watch_ids: Vec<u32> = state
+            .path_watches
+            .borrow()
+            .iter()
+            .filter(|(_, plugin)| *plugin == plugin_name)
+            .map(|(id, _)| *id)
+            .collect();
+        for watch_id in watch_ids {
+            state.remove_path_watch(watch_id);
+        }
+    }
+
+    /// Remove the gutter columns of an unloaded plugin
+    pub fn remove_plugin_gutter_columns(&self, plugin_name: &str) {
+        let state = self.runtime_state.borrow();
+        let names: Vec<String> = state
+            .gutter_columns
+            .borrow()
+            .iter()
+            .filter(|(_, plugin)| *plugin == plugin_name)
+            .map(|(name, _)| name.clone())
+            .collect();
+        for name in names {
+            state.remove_gutter_column(&name);
+        }
+    }
+
+    /// Remove the file explorer actions of an unloaded plugin
+    pub fn remove_plugin_explorer_actions(&self, plugin_name: &str) {
+        let state = self.runtime_state.borrow();
+        let ids: Vec<String> = state
+            .explorer_actions
+            .borrow()
+            .iter()
+            .filter(|(_, plugin)| *plugin == plugin_name)
+            .map(|(id, _)| id.clone())
+            .collect();
+        for id in ids {
+            state.remove_explorer_action(&id);
+        }
+    }
+
+    /// Finish the progress entries of an unloaded plugin
+    pub fn end_plugin_progress(&self, plugin_name: &str) {
+        let state = self.runtime_state.borrow();
+        let progress_ids: Vec<u32> = state
+            .progress
+            .borrow()
+            .iter()
+            .filter(|(_, plugin)| *plugin == plugin_name)
+            .map(|(id, _)| *id)
+            .collect();
+        for progress_id in progress_ids {
+            state.remove_progress(progress_id, true);
+        }
+    }
+
+    /// Forget the handler of a chunked buffer read once its last piece is in
+    pub fn end_buffer_chunk_read(&self, read_id: u32) {
+        self.event_handlers
+            .borrow_mut()
+            .remove(&buffer_chunks_hook_name(read_id));
+    }
+
     /// Deliver a response to a pending async operation
     ///
     /// This is called by the editor after processing a command that requires a response.
@@ -4055,6 +6640,23 @@ impl TypeScriptRuntime {
             crate::services::plugins::api::PluginResponse::BufferText { request_id, .. } => {
                 *request_id
             }
+            crate::services::plugins::api::PluginResponse::PendingEditsReviewed {
+                request_id,
+                ..
+            } => *request_id,
+            crate::services::plugins::api::PluginResponse::ActionsExecuted {
+                request_id, ..
+            } => *request_id,
+            crate::services::plugins::api::PluginResponse::Keymap { request_id, .. } => *request_id,
+            crate::services::plugins::api::PluginResponse::LineCol { request_id, .. } => {
+                *request_id
+            }
+            crate::services::plugins::api::PluginResponse::ByteOffset { request_id, .. } => {
+                *request_id
+            }
+            crate::services::plugins::api::PluginResponse::BufferCreated { request_id, .. } => {
+                *request_id
+            }
         };
 
         let sender = {
@@ -4091,6 +6693,16 @@ impl TypeScriptRuntime {
         Ok(())
     }
 
+    /// Keep transpiled plugins in `dir`, so unchanged plugins skip transpiling
+    pub fn set_transpile_cache_dir(&mut self, dir: Option<PathBuf>) {
+        self.transpiler.set_cache_dir(dir);
+    }
+
+    /// How many modules were transpiled rather than taken from the cache
+    pub fn transpile_count(&self) -> usize {
+        self.transpiler.transpile_count()
+    }
+
     /// Load and execute a TypeScript/JavaScript module file
     pub async fn load_module(&mut self, path: &str) -> Result<()> {
         self.load_module_with_source(path, "").await
@@ -4109,12 +6721,29 @@ impl TypeScriptRuntime {
             .execute_script("<set_pending_editor>", set_editor)
             .map_err(|e| anyhow!("Failed to set pending editor: {}", e))?;
 
-        let module_specifier = deno_core::resolve_path(
+        let mut module_specifier = deno_core::resolve_path(
             path,
             &std::env::current_dir().map_err(|e| anyhow!("Failed to get cwd: {}", e))?,
         )
         .map_err(|e| anyhow!("Failed to resolve module path '{}': {}", path, e))?;
 
+        // A module already in the module map isn't evaluated again, so a
+        // reloaded plugin gets a fresh specifier to run its code (and
+        // re-register its handlers) once more
+        let loads = self.module_loads.entry(path.to_string()).or_insert(0);
+        if *loads > 0 {
+            module_specifier.set_query(Some(&format!("reload={}", loads)));
+        }
+        *loads += 1;
+
+        // Transpile up front, so a syntax error is reported with its location
+        // rather than wrapped in a module loading error
+        if let Ok(file_path) = module_specifier.to_file_path() {
+            if file_path.extension().and_then(|s| s.to_str()) == Some("ts") {
+                self.transpiler.prepare(&module_specifier, &file_path)?;
+            }
+        }
+
         // Use load_side_es_module for plugins (allows multiple modules to be loaded)
         let mod_id = self
             .js_runtime
@@ -4150,13 +6779,24 @@ impl TypeScriptRuntime {
 
     /// Execute a global function by name (for plugin actions)
     pub async fn execute_action(&mut self, action_name: &str) -> Result<()> {
+        self.execute_action_with_options(action_name, &ActionOptions::default())
+            .await
+    }
+
+    /// Execute a global function by name, passing it `options`
+    pub async fn execute_action_with_options(
+        &mut self,
+        action_name: &str,
+        options: &ActionOptions,
+    ) -> Result<()> {
         // Action functions are defined by plugins and have captured their scoped
         // editor reference in closures, so no context setup is needed here.
+        let options = serde_json::to_string(options)?;
         let code = format!(
             r#"
             (async () => {{
                 if (typeof globalThis.{} === 'function') {{
-                    const result = globalThis.{}();
+                    const result = globalThis.{}({});
                     if (result instanceof Promise) {{
                         await result;
                     }}
@@ -4165,7 +6805,7 @@ impl TypeScriptRuntime {
                 }}
             }})();
             "#,
-            action_name, action_name, action_name
+            action_name, action_name, options, action_name
         );
 
         self.execute_script("<action>", &code).await
@@ -4185,53 +6825,58 @@ impl TypeScriptRuntime {
     /// * `Ok(false)` if any handler returned false (cancel)
     /// * `Err` if handler execution failed
     pub async fn emit(&mut self, event_name: &str, event_data: &str) -> Result<bool> {
-        let emit_start = std::time::Instant::now();
-        let handlers = self.event_handlers.borrow().get(event_name).cloned();
-
-        if let Some(handler_names) = handlers {
-            if handler_names.is_empty() {
-                return Ok(true);
+        if !self.has_handlers(event_name) {
+            return Ok(true);
+        }
+        match serde_json::from_str(event_data) {
+            Ok(data) => self.emit_value(event_name, data).await,
+            Err(e) => {
+                tracing::error!("Invalid data for event '{}': {}", event_name, e);
+                Ok(true)
             }
+        }
+    }
 
-            for handler_name in &handler_names {
-                let call_start = std::time::Instant::now();
-
-                // Use execute_script to call the event dispatcher
-                // This escapes the handler name and embeds the event data as JSON
-                let script = format!(
-                    "__eventDispatcher({}, {})",
-                    serde_json::to_string(handler_name).unwrap_or_else(|_| "\"\"".to_string()),
-                    event_data
-                );
+    /// Emit an event to all registered handlers, like [`Self::emit`]
+    ///
+    /// The data is converted to a JS object once and the same object is
+    /// passed to every handler.
+    pub async fn emit_value(&mut self, event_name: &str, data: serde_json::Value) -> Result<bool> {
+        let handlers = self.get_registered_handlers(event_name);
+        self.emit_value_to(event_name, data, handlers).await
+    }
+
+    /// Emit an event to some of its registered handlers only, like
+    /// [`Self::emit_value`]
+    pub async fn emit_value_to(
+        &mut self,
+        event_name: &str,
+        data: serde_json::Value,
+        handlers: Vec<EventHandlerInfo>,
+    ) -> Result<bool> {
+        let emit_start = std::time::Instant::now();
+        if handlers.is_empty() {
+            return Ok(true);
+        }
+        let handler_count = handlers.len();
 
-                match self.js_runtime.execute_script("<emit>", script) {
-                    Ok(_) => {
-                        let call_elapsed = call_start.elapsed();
-                        // Don't poll event loop here - the plugin thread's main loop
-                        // will poll it periodically to allow long-running promises
-                        // (like process spawns) to make progress.
-                        tracing::trace!(
-                            event = event_name,
-                            handler = handler_name,
-                            call_us = call_elapsed.as_micros(),
-                            "emit handler timing"
-                        );
-                    }
-                    Err(e) => {
-                        tracing::error!(
-                            "Failed to call event handler '{}' for '{}': {:?}",
-                            handler_name,
-                            event_name,
-                            e
-                        );
-                    }
-                }
-            }
+        self.runtime_state.borrow_mut().pending_event = Some(PendingEvent { handlers, data });
+        // Don't poll event loop here - the plugin thread's main loop
+        // will poll it periodically to allow long-running promises
+        // (like process spawns) to make progress.
+        let result = self
+            .js_runtime
+            .execute_script("<emit>", FastString::from_static(DISPATCH_EVENT_SCRIPT));
+        // Not taken if the dispatcher failed before taking it
+        self.runtime_state.borrow_mut().pending_event = None;
+        if let Err(e) = result {
+            tracing::error!("Failed to dispatch event '{}': {:?}", event_name, e);
         }
 
         let emit_elapsed = emit_start.elapsed();
         tracing::trace!(
             event = event_name,
+            handlers = handler_count,
             total_us = emit_elapsed.as_micros(),
             "emit total timing"
         );
@@ -4240,7 +6885,7 @@ impl TypeScriptRuntime {
     }
 
     /// Get the list of registered handlers for an event
-    pub fn get_registered_handlers(&self, event_name: &str) -> Vec<String> {
+    pub fn get_registered_handlers(&self, event_name: &str) -> Vec<EventHandlerInfo> {
         self.event_handlers
             .borrow()
             .get(event_name)
@@ -4273,6 +6918,15 @@ impl TypeScriptRuntime {
         }
     }
 
+    /// Send a command to the editor from outside any plugin code
+    pub fn send_command(&mut self, command: PluginCommand) {
+        let op_state = self.js_runtime.op_state();
+        let op_state = op_state.borrow();
+        if let Some(runtime_state) = op_state.try_borrow::<Rc<RefCell<TsRuntimeState>>>() {
+            let _ = runtime_state.borrow().command_sender.send(command);
+        }
+    }
+
     /// Send a status message to the editor UI
     pub fn send_status(&mut self, message: String) {
         let op_state = self.js_runtime.op_state();
@@ -4289,7 +6943,7 @@ impl TypeScriptRuntime {
 // === TypeScript Plugin Manager ===
 
 use crate::input::command_registry::CommandRegistry;
-use crate::services::plugins::hooks::{hook_args_to_json, HookArgs, HookRegistry};
+use crate::services::plugins::hooks::{hook_args_to_value, HookArgs, HookRegistry};
 use std::path::{Path, PathBuf};
 
 /// Information about a loaded TypeScript plugin
@@ -4390,9 +7044,14 @@ impl TypeScriptPluginManager {
             let prefix = format!("{}:", name);
             self.commands.read().unwrap().unregister_by_prefix(&prefix);
 
+            self.runtime.unwatch_plugin_paths(name);
+            self.runtime.remove_plugin_gutter_columns(name);
+            self.runtime.remove_plugin_explorer_actions(name);
+            self.runtime.end_plugin_progress(name);
+            self.runtime.remove_plugin_handlers(name);
+
             // Note: We can't truly unload JavaScript modules from V8,
             // but we can remove the plugin from our tracking
-            // Future: could clear registered hooks for this plugin
 
             Ok(())
         } else {
@@ -4473,11 +7132,15 @@ impl TypeScriptPluginManager {
     ///
     /// This converts HookArgs to JSON and emits to all registered TypeScript handlers.
     pub async fn run_hook(&mut self, hook_name: &str, args: &HookArgs) -> Result<()> {
-        // Convert HookArgs to JSON
-        let json_data = hook_args_to_json(args)?;
+        // Nothing to convert when no handler listens
+        if !self.runtime.has_handlers(hook_name) {
+            return Ok(());
+        }
 
         // Emit to TypeScript handlers
-        self.runtime.emit(hook_name, &json_data).await?;
+        self.runtime
+            .emit_value(hook_name, hook_args_to_value(args))
+            .await?;
 
         Ok(())
     }
@@ -4568,6 +7231,7 @@ impl TypeScriptPluginManager {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::services::plugins::hooks::hook_args_to_json;
 
     #[tokio::test]
     async fn test_typescript_runtime_creation() {
@@ -4664,13 +7328,81 @@ mod tests {
             .await
             .unwrap();
 
-        // Execute the async action
-        let result = runtime.execute_action("my_async_action").await;
-        assert!(
-            result.is_ok(),
-            "Failed to execute async action: {:?}",
-            result
-        );
+        // Execute the async action
+        let result = runtime.execute_action("my_async_action").await;
+        assert!(
+            result.is_ok(),
+            "Failed to execute async action: {:?}",
+            result
+        );
+    }
+
+    #[tokio::test]
+    async fn test_execute_action_with_options() {
+        let mut runtime = TypeScriptRuntime::new().unwrap();
+
+        runtime
+            .execute_script(
+                "<define_action>",
+                r#"
+                globalThis.my_dry_run_action = function(options) {
+                    if (options.dry_run !== true) {
+                        throw new Error("expected a dry run");
+                    }
+                };
+                "#,
+            )
+            .await
+            .unwrap();
+
+        let options = ActionOptions {
+            dry_run: true,
+            ..Default::default()
+        };
+        let result = runtime
+            .execute_action_with_options("my_dry_run_action", &options)
+            .await;
+        assert!(result.is_ok(), "Dry run not passed: {:?}", result);
+
+        // Without options the action is told it isn't a dry run
+        let result = runtime.execute_action("my_dry_run_action").await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_execute_action_with_args() {
+        let mut runtime = TypeScriptRuntime::new().unwrap();
+
+        runtime
+            .execute_script(
+                "<define_action>",
+                r#"
+                globalThis.my_args_action = function(options) {
+                    if (options.args === undefined) {
+                        throw new Error("no args");
+                    }
+                    if (options.args.who !== "Ada" || options.args.times !== 2) {
+                        throw new Error("wrong args " + JSON.stringify(options.args));
+                    }
+                };
+                "#,
+            )
+            .await
+            .unwrap();
+
+        let args = serde_json::json!({"who": "Ada", "times": 2});
+        let options = ActionOptions {
+            args: args.as_object().cloned(),
+            ..Default::default()
+        };
+        let result = runtime
+            .execute_action_with_options("my_args_action", &options)
+            .await;
+        assert!(result.is_ok(), "Args not passed: {:?}", result);
+
+        // Commands without parameters get no args
+        let result = runtime.execute_action("my_args_action").await;
+        assert!(result.is_err());
     }
 
     #[tokio::test]
@@ -4693,6 +7425,8 @@ mod tests {
                     path: Some(PathBuf::from("/test/file.rs")),
                     modified: true,
                     length: 1000,
+                    read_only_reason: None,
+                    pinned: false,
                 },
             );
             snapshot.primary_cursor = Some(CursorInfo {
@@ -4830,7 +7564,7 @@ mod tests {
                 assert_eq!(namespace.as_ref().map(|n| n.as_str()), Some("test-overlay"));
                 assert_eq!(range.start, 0);
                 assert_eq!(range.end, 50);
-                assert_eq!(*color, (255, 0, 0));
+                assert_eq!(*color, DecorationColor::Rgb(255, 0, 0));
                 assert_eq!(*bg_color, None);
                 assert!(*underline);
                 assert!(!*bold);
@@ -4843,7 +7577,7 @@ mod tests {
         match &commands[4] {
             PluginCommand::RemoveOverlay { buffer_id, handle } => {
                 assert_eq!(buffer_id.0, 42);
-                assert_eq!(handle.as_str(), "test-overlay");
+                assert_eq!(handle.to_string(), "test-overlay");
             }
             _ => panic!("Expected RemoveOverlay command"),
         }
@@ -4948,7 +7682,7 @@ mod tests {
 
         // Check RegisterCommand
         match &commands[0] {
-            PluginCommand::RegisterCommand { command } => {
+            PluginCommand::RegisterCommand { command, .. } => {
                 assert_eq!(command.name, "My Plugin Command");
                 assert_eq!(command.description, "A test command from TypeScript");
                 match &command.action {
@@ -5011,7 +7745,7 @@ mod tests {
         assert_eq!(commands.len(), 1);
 
         match &commands[0] {
-            PluginCommand::RegisterCommand { command } => {
+            PluginCommand::RegisterCommand { command, .. } => {
                 assert_eq!(command.name, "Global Command");
                 assert!(
                     command.contexts.is_empty(),
@@ -5047,7 +7781,7 @@ mod tests {
 
         let commands: Vec<_> = rx.try_iter().collect();
         match &commands[0] {
-            PluginCommand::RegisterCommand { command } => {
+            PluginCommand::RegisterCommand { command, .. } => {
                 assert_eq!(command.contexts.len(), 6);
                 assert!(command
                     .contexts
@@ -5097,7 +7831,7 @@ mod tests {
 
         let commands: Vec<_> = rx.try_iter().collect();
         match &commands[0] {
-            PluginCommand::RegisterCommand { command } => {
+            PluginCommand::RegisterCommand { command, .. } => {
                 // Only normal and popup should be recognized
                 assert_eq!(command.contexts.len(), 2);
                 assert!(command
@@ -5111,6 +7845,235 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_register_command_dry_run_option() {
+        let (tx, rx) = std::sync::mpsc::channel();
+        let state_snapshot = Arc::new(RwLock::new(EditorStateSnapshot::new()));
+        let mut runtime = TypeScriptRuntime::with_state(state_snapshot, tx).unwrap();
+
+        let result = runtime
+            .execute_script(
+                "<test_dry_run_option>",
+                r#"
+                const editor = globalThis._createPluginEditor('test');
+                editor.registerCommand("Clean Up", "Delete stale files", "clean_up", "", { dry_run: true });
+                editor.registerCommand("Tidy", "Tidy files", "tidy");
+                "#,
+            )
+            .await;
+        assert!(result.is_ok(), "Registering failed: {:?}", result);
+
+        let dry_runs: Vec<bool> = rx
+            .try_iter()
+            .filter_map(|command| match command {
+                PluginCommand::RegisterCommand { dry_run, .. } => Some(dry_run),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(dry_runs, vec![true, false]);
+    }
+
+    #[tokio::test]
+    async fn test_register_command_args() {
+        let (tx, rx) = std::sync::mpsc::channel();
+        let state_snapshot = Arc::new(RwLock::new(EditorStateSnapshot::new()));
+        let mut runtime = TypeScriptRuntime::with_state(state_snapshot, tx).unwrap();
+
+        let result = runtime
+            .execute_script(
+                "<test_command_args>",
+                r#"
+                const editor = globalThis._createPluginEditor('test');
+                editor.registerCommand("Greet", "Greet someone", "greet", "", {
+                    args: [
+                        { name: "who", type: "string" },
+                        { name: "mood", type: "choice", choices: ["glad", "sad"], optional: true },
+                    ],
+                });
+                let threw = false;
+                try {
+                    editor.registerCommand("Bad", "Bad", "bad", "", { args: [{ name: "x", type: "bool" }] });
+                } catch (e) {
+                    threw = String(e).includes("'x'");
+                }
+                if (!threw) {
+                    throw new Error("an invalid parameter type was accepted");
+                }
+                "#,
+            )
+            .await;
+        assert!(result.is_ok(), "Registering failed: {:?}", result);
+
+        let registered: Vec<(String, Vec<CommandArg>)> = rx
+            .try_iter()
+            .filter_map(|command| match command {
+                PluginCommand::RegisterCommand { command, args, .. } => Some((command.name, args)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            registered,
+            vec![(
+                "Greet".to_string(),
+                vec![
+                    CommandArg {
+                        name: "who".to_string(),
+                        arg_type: CommandArgType::String,
+                        optional: false,
+                    },
+                    CommandArg {
+                        name: "mood".to_string(),
+                        arg_type: CommandArgType::Choice(vec!["glad".into(), "sad".into()]),
+                        optional: true,
+                    },
+                ]
+            )]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_progress_ops_and_unload() {
+        let (tx, rx) = std::sync::mpsc::channel();
+        let state_snapshot = Arc::new(RwLock::new(EditorStateSnapshot::new()));
+        let mut runtime = TypeScriptRuntime::with_state(state_snapshot, tx).unwrap();
+
+        let result = runtime
+            .execute_script(
+                "<test_progress>",
+                r#"
+                const editor = globalThis._createPluginEditor('indexer');
+                globalThis.first = editor.progressStart({ title: "Indexing", cancellable: true });
+                editor.progressOnCancel(first, "onIndexCancelled");
+                editor.progressUpdate(first, 42.4);
+                editor.progressUpdate(first, "src/lib.rs");
+                globalThis.second = editor.progressStart({ title: "Scanning" });
+                editor.progressFinish(second);
+                if (editor.progressUpdate(second, 10) || editor.progressFinish(second)) {
+                    throw new Error("a finished progress still accepts updates");
+                }
+                "#,
+            )
+            .await;
+        assert!(result.is_ok(), "Progress ops failed: {:?}", result);
+
+        let commands: Vec<_> = rx.try_iter().collect();
+        let (first, cancellable) = match &commands[0] {
+            PluginCommand::StartProgress {
+                progress_id,
+                plugin,
+                title,
+                cancellable,
+            } => {
+                assert_eq!(plugin, "indexer");
+                assert_eq!(title, "Indexing");
+                (*progress_id, *cancellable)
+            }
+            other => panic!("Expected StartProgress, got {:?}", other),
+        };
+        assert!(cancellable);
+        assert!(matches!(
+            &commands[1],
+            PluginCommand::UpdateProgress { progress_id, percentage: Some(42), message: None }
+                if *progress_id == first
+        ));
+        assert!(matches!(
+            &commands[2],
+            PluginCommand::UpdateProgress { percentage: None, message: Some(m), .. }
+                if m == "src/lib.rs"
+        ));
+        assert!(matches!(
+            &commands[3],
+            PluginCommand::StartProgress {
+                cancellable: false,
+                ..
+            }
+        ));
+        assert!(matches!(
+            &commands[4],
+            PluginCommand::FinishProgress {
+                abandoned: false,
+                ..
+            }
+        ));
+        assert_eq!(commands.len(), 5);
+        assert!(runtime.has_handlers(&progress_cancel_hook_name(first)));
+
+        // Unloading the plugin finishes what it left behind
+        runtime.end_plugin_progress("indexer");
+        let commands: Vec<_> = rx.try_iter().collect();
+        assert!(matches!(
+            commands.as_slice(),
+            [PluginCommand::FinishProgress { progress_id, abandoned: true }] if *progress_id == first
+        ));
+        assert!(!runtime.has_handlers(&progress_cancel_hook_name(first)));
+    }
+
+    #[tokio::test]
+    async fn test_explorer_action_ops_and_unload() {
+        let (tx, rx) = std::sync::mpsc::channel();
+        let state_snapshot = Arc::new(RwLock::new(EditorStateSnapshot::new()));
+        let mut runtime = TypeScriptRuntime::with_state(state_snapshot, tx).unwrap();
+
+        let result = runtime
+            .execute_script(
+                "<test_explorer_actions>",
+                r#"
+                const tools = globalThis._createPluginEditor('tools');
+                tools.registerExplorerAction({ id: "count", label: "Count Lines", when: "file", handler: "onCount" });
+                tools.registerExplorerAction({ id: "stats", label: "Stats", handler: "onStats" });
+                for (const options of [
+                    { id: "bad", label: "Bad", when: "folder", handler: "onBad" },
+                    { id: "", label: "Empty", handler: "onEmpty" },
+                ]) {
+                    let threw = false;
+                    try { tools.registerExplorerAction(options); } catch (e) { threw = true; }
+                    if (!threw) throw new Error(`registered ${JSON.stringify(options)}`);
+                }
+                const other = globalThis._createPluginEditor('other');
+                let threw = false;
+                try {
+                    other.registerExplorerAction({ id: "count", label: "Mine", handler: "onMine" });
+                } catch (e) {
+                    threw = true;
+                }
+                if (!threw || other.unregisterExplorerAction("count")) {
+                    throw new Error("another plugin's action was taken over");
+                }
+                "#,
+            )
+            .await;
+        assert!(result.is_ok(), "Explorer action ops failed: {:?}", result);
+
+        let commands: Vec<_> = rx.try_iter().collect();
+        assert!(matches!(
+            commands.as_slice(),
+            [
+                PluginCommand::RegisterExplorerAction {
+                    when: ExplorerActionTarget::File,
+                    ..
+                },
+                PluginCommand::RegisterExplorerAction {
+                    when: ExplorerActionTarget::Any,
+                    ..
+                },
+            ]
+        ));
+        assert!(runtime.has_handlers(&explorer_action_hook_name("count")));
+
+        // Unloading the plugin removes its entries
+        runtime.remove_plugin_explorer_actions("tools");
+        let mut removed: Vec<String> = rx
+            .try_iter()
+            .filter_map(|command| match command {
+                PluginCommand::UnregisterExplorerAction { id } => Some(id),
+                _ => None,
+            })
+            .collect();
+        removed.sort();
+        assert_eq!(removed, vec!["count", "stats"]);
+        assert!(!runtime.has_handlers(&explorer_action_hook_name("count")));
+    }
+
     #[tokio::test]
     async fn test_open_file_with_zero_values() {
         let (tx, rx) = std::sync::mpsc::channel();
@@ -5198,6 +8161,44 @@ mod tests {
         }
     }
 
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_open_file_decodes_file_uris() {
+        let (tx, rx) = std::sync::mpsc::channel();
+        let state_snapshot = Arc::new(RwLock::new(EditorStateSnapshot::new()));
+        let mut runtime = TypeScriptRuntime::with_state(state_snapshot, tx).unwrap();
+
+        let result = runtime
+            .execute_script(
+                "<test_file_uri>",
+                r#"
+                const editor = globalThis._createPluginEditor('test');
+                editor.openFile("file:///home/me/My%20Project/src/main.rs", 3);
+                let threw = false;
+                try {
+                    editor.openFile("https://example.com/main.rs");
+                } catch (e) {
+                    threw = true;
+                }
+                if (!threw) {
+                    throw new Error("openFile accepted an https URI");
+                }
+                "#,
+            )
+            .await;
+        assert!(result.is_ok(), "{:?}", result);
+
+        let commands: Vec<_> = rx.try_iter().collect();
+        assert_eq!(commands.len(), 1);
+        match &commands[0] {
+            PluginCommand::OpenFileAtLocation { path, line, .. } => {
+                assert_eq!(path.to_str().unwrap(), "/home/me/My Project/src/main.rs");
+                assert_eq!(*line, Some(3));
+            }
+            _ => panic!("Expected OpenFileAtLocation"),
+        }
+    }
+
     #[tokio::test]
     async fn test_register_command_case_insensitive_contexts() {
         let (tx, rx) = std::sync::mpsc::channel();
@@ -5223,7 +8224,7 @@ mod tests {
 
         let commands: Vec<_> = rx.try_iter().collect();
         match &commands[0] {
-            PluginCommand::RegisterCommand { command } => {
+            PluginCommand::RegisterCommand { command, .. } => {
                 assert_eq!(command.contexts.len(), 3);
                 assert!(command
                     .contexts
@@ -5719,8 +8720,17 @@ mod tests {
                 if (handlers.length !== 1) {
                     throw new Error(`Expected 1 handler, got ${handlers.length}`);
                 }
-                if (handlers[0] !== "onBufferSave") {
-                    throw new Error(`Expected handler 'onBufferSave', got '${handlers[0]}'`);
+                if (handlers[0].handler !== "onBufferSave") {
+                    throw new Error(`Expected handler 'onBufferSave', got '${handlers[0].handler}'`);
+                }
+                if (handlers[0].plugin !== "test") {
+                    throw new Error(`Expected plugin 'test', got '${handlers[0].plugin}'`);
+                }
+
+                // Registering the same handler again doesn't add it twice
+                editor.on("buffer_save", "onBufferSave");
+                if (editor.getHandlers("buffer_save").length !== 1) {
+                    throw new Error("Duplicate registration should be ignored");
                 }
 
                 // Register another handler
@@ -5906,6 +8916,96 @@ mod tests {
         assert!(verify.is_ok(), "Verify failed: {:?}", verify);
     }
 
+    #[tokio::test]
+    async fn test_hook_dispatch_order_and_false_return() {
+        let mut runtime = TypeScriptRuntime::new().unwrap();
+
+        // The middle handler cancels; the others still run, in order, and
+        // what the first changes in its data isn't seen by the others
+        let setup = runtime
+            .execute_script(
+                "<test_hook_dispatch_setup>",
+                r#"
+                const editor = globalThis._createPluginEditor('test');
+                globalThis.calls = [];
+                globalThis.first = function(data) { calls.push(['first', data.text]); data.text = 'changed'; };
+                globalThis.second = function(data) { calls.push(['second', data.text]); return false; };
+                globalThis.third = async function(data) { calls.push(['third', data.text]); return true; };
+                editor.on("dispatch_event", "first");
+                editor.on("dispatch_event", "second");
+                editor.on("dispatch_event", "third");
+                "#,
+            )
+            .await;
+        assert!(setup.is_ok(), "Setup failed: {:?}", setup);
+
+        // Data that would break a script it was spliced into
+        let text = "quote \" backslash \\ </script> \u{2028} ${x} `";
+        let data = serde_json::json!({ "text": text });
+        let emit_result = runtime.emit("dispatch_event", &data.to_string()).await;
+        assert!(emit_result.unwrap(), "Emit should return true");
+        let emit_result = runtime.emit_value("dispatch_event", data).await;
+        assert!(emit_result.unwrap(), "Emit should return true");
+        assert!(runtime.runtime_state.borrow().pending_event.is_none());
+
+        let verify = runtime
+            .execute_script(
+                "<test_hook_dispatch_verify>",
+                &format!(
+                    r#"
+                const text = {};
+                const names = calls.map(c => c[0]).join(',');
+                if (names !== 'first,second,third,first,second,third') {{
+                    throw new Error(`Unexpected calls: ${{names}}`);
+                }}
+                if (!calls.every(c => c[1] === text)) {{
+                    throw new Error(`Unexpected data: ${{JSON.stringify(calls)}}`);
+                }}
+                "#,
+                    serde_json::to_string(text).unwrap()
+                ),
+            )
+            .await;
+        assert!(verify.is_ok(), "Verify failed: {:?}", verify);
+    }
+
+    #[tokio::test]
+    async fn test_hook_dispatch_without_handlers() {
+        let mut runtime = TypeScriptRuntime::new().unwrap();
+
+        // Nothing listens, so even data that isn't JSON is never looked at
+        assert!(runtime.emit("quiet_event", "not json").await.unwrap());
+        assert!(runtime.runtime_state.borrow().pending_event.is_none());
+
+        // A handler that isn't defined is skipped with a warning
+        let setup = runtime
+            .execute_script(
+                "<test_hook_dispatch_missing_setup>",
+                r#"
+                const editor = globalThis._createPluginEditor('test');
+                globalThis.seen = 0;
+                globalThis.defined = function(data) { seen += data.n; };
+                editor.on("partial_event", "undefinedHandler");
+                editor.on("partial_event", "defined");
+                "#,
+            )
+            .await;
+        assert!(setup.is_ok(), "Setup failed: {:?}", setup);
+        assert!(runtime.emit("partial_event", r#"{"n": 2}"#).await.unwrap());
+
+        let verify = runtime
+            .execute_script(
+                "<test_hook_dispatch_missing_verify>",
+                r#"
+                if (seen !== 2) {
+                    throw new Error(`Expected seen=2, got ${seen}`);
+                }
+                "#,
+            )
+            .await;
+        assert!(verify.is_ok(), "Verify failed: {:?}", verify);
+    }
+
     // === TypeScriptPluginManager Tests ===
 
     #[tokio::test]
@@ -6168,6 +9268,86 @@ mod tests {
         assert_eq!(manager.list_plugins().len(), 0);
     }
 
+    #[tokio::test]
+    async fn test_ts_plugin_manager_reload_plugin_replaces_handlers() {
+        use std::io::Write;
+        use tempfile::NamedTempFile;
+
+        let hooks = Arc::new(RwLock::new(HookRegistry::new()));
+        let commands = Arc::new(RwLock::new(CommandRegistry::new()));
+
+        let mut manager = TypeScriptPluginManager::new(hooks, commands).unwrap();
+
+        let mut temp_file = NamedTempFile::with_suffix(".js").unwrap();
+        writeln!(
+            temp_file,
+            r#"
+            const editor = getEditor();
+            globalThis.onReloadSave = (data) => {{
+                editor.setStatus("saved " + data.path);
+            }};
+            editor.on("buffer_save", "onReloadSave");
+            editor.on("buffer_save", "onReloadSave");
+            editor.on("cursor_moved", "onReloadSave");
+            "#
+        )
+        .unwrap();
+        temp_file.flush().unwrap();
+
+        let plugin_name = temp_file
+            .path()
+            .file_stem()
+            .unwrap()
+            .to_str()
+            .unwrap()
+            .to_string();
+
+        manager.load_plugin(temp_file.path()).await.unwrap();
+        let handlers = manager.runtime.get_registered_handlers("buffer_save");
+        assert_eq!(
+            handlers,
+            vec![EventHandlerInfo {
+                handler: "onReloadSave".to_string(),
+                plugin: plugin_name.clone(),
+            }]
+        );
+
+        // Reloading runs the plugin again without leaving its old handlers behind
+        for _ in 0..2 {
+            manager.reload_plugin(&plugin_name).await.unwrap();
+            assert_eq!(
+                manager.runtime.get_registered_handlers("buffer_save"),
+                handlers
+            );
+            assert_eq!(
+                manager.runtime.get_registered_handlers("cursor_moved"),
+                handlers
+            );
+        }
+
+        // Every remaining handler is defined, so emitting calls each once
+        manager.process_commands();
+        manager
+            .runtime
+            .emit("buffer_save", r#"{"path": "a.txt"}"#)
+            .await
+            .unwrap();
+        manager.runtime.poll_event_loop_once();
+        let saves = manager
+            .process_commands()
+            .into_iter()
+            .filter(|cmd| {
+                matches!(cmd, PluginCommand::SetStatus { message } if message == "saved a.txt")
+            })
+            .count();
+        assert_eq!(saves, 1);
+
+        // Unloading removes them
+        manager.unload_plugin(&plugin_name).unwrap();
+        assert!(!manager.runtime.has_handlers("buffer_save"));
+        assert!(!manager.runtime.has_handlers("cursor_moved"));
+    }
+
     #[tokio::test]
     async fn test_emit_performance() {
         use std::time::Instant;
@@ -6880,4 +10060,78 @@ mod tests {
         // Shutdown
         handle.shutdown();
     }
+
+    #[test]
+    fn test_plugin_thread_reports_syntax_error_and_loads_other_plugins() {
+        use crate::services::plugins::thread::PluginThreadHandle;
+        use tempfile::TempDir;
+
+        let temp_dir = TempDir::new().unwrap();
+        let plugins_dir = temp_dir.path().join("plugins");
+        std::fs::create_dir(&plugins_dir).unwrap();
+        std::fs::write(
+            plugins_dir.join("broken.ts"),
+            "const editor = getEditor();\n\nconst value: number = (1 + ;\n",
+        )
+        .unwrap();
+        std::fs::write(
+            plugins_dir.join("good.ts"),
+            "const editor = getEditor();\neditor.setStatus(\"good plugin loaded\");\n",
+        )
+        .unwrap();
+
+        let commands = Arc::new(RwLock::new(CommandRegistry::new()));
+        let mut handle = PluginThreadHandle::spawn_with_transpile_cache(
+            commands,
+            Some(temp_dir.path().join("cache")),
+        )
+        .unwrap();
+
+        let errors = handle.load_plugins_from_dir(&plugins_dir);
+        assert_eq!(errors.len(), 1, "{:?}", errors);
+        assert_eq!(errors[0].plugin.as_deref(), Some("broken"));
+        let message = &errors[0].message;
+        let location = format!("{}:3:", plugins_dir.join("broken.ts").display());
+        assert!(message.starts_with(&location), "{message}");
+        assert!(
+            message.contains("3 | const value: number = (1 + ;"),
+            "{message}"
+        );
+        assert!(message.lines().last().unwrap().ends_with('^'), "{message}");
+
+        let loaded: Vec<String> = handle.list_plugins().into_iter().map(|p| p.name).collect();
+        assert_eq!(loaded, vec!["good".to_string()]);
+        let cmds = handle.process_commands();
+        assert!(cmds.iter().any(|cmd| matches!(
+            cmd,
+            PluginCommand::SetStatus { message } if message == "good plugin loaded"
+        )));
+
+        handle.shutdown();
+    }
+
+    #[tokio::test]
+    async fn test_warm_transpile_cache_skips_transpiling() {
+        use tempfile::TempDir;
+
+        let temp_dir = TempDir::new().unwrap();
+        let plugin_path = temp_dir.path().join("cached.ts");
+        std::fs::write(
+            &plugin_path,
+            "const editor = getEditor();\nconst greeting: string = \"hi\";\neditor.debug(greeting);\n",
+        )
+        .unwrap();
+        let cache_dir = temp_dir.path().join("cache");
+        let path = plugin_path.to_str().unwrap();
+
+        let mut cold = TypeScriptRuntime::new().unwrap();
+        cold.set_transpile_cache_dir(Some(cache_dir.clone()));
+        cold.load_module_with_source(path, "cached").await.unwrap();
+        assert_eq!(cold.transpile_count(), 1);
+
+        let mut warm = TypeScriptRuntime::new().unwrap();
+        warm.set_transpile_cache_dir(Some(cache_dir));
+        warm.load_module_with_source(path, "cached").await.unwrap();
+        assert_eq!(warm.transpile_count(), 0);
+    }
 }
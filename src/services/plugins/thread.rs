@@ -8,17 +8,39 @@
 //! - Plugin thread owns JsRuntime and persistent tokio runtime
 //! - Results are sent back via the existing PluginCommand channel
 //! - Async operations complete naturally without runtime destruction
+//!
+//! Hook ordering:
+//! - Hooks emitted by the editor are queued, not sent, and go to the plugin
+//!   thread in batches from [`PluginThreadHandle::dispatch_hooks`]
+//! - A batch is only sent once the editor has drained the commands of the
+//!   previous one: after running a batch the plugin thread stores its number
+//!   in `hook_batches_done`, after everything its handlers sent, and the editor
+//!   reads that number before draining the command channel
+//! - So the commands a handler sends before it returns are applied, and the
+//!   state snapshot refreshed, before the handlers of any later batch run
+//!
+//! Hook loops:
+//! - Hooks emitted while the editor applies the commands of a batch are one
+//!   deeper in its chain than that batch, every other hook is at depth 0; a
+//!   batch goes at the depth of its deepest hook
+//! - The plugin thread runs each handler past a [`HookLoopGuard`], which cuts
+//!   off handlers that set themselves off too deep or too often in one chain
 
 use crate::input::command_registry::CommandRegistry;
-use crate::services::plugins::api::{EditorStateSnapshot, PluginCommand};
-use crate::services::plugins::hooks::{hook_args_to_json, HookArgs};
+use crate::services::plugins::api::{ActionOptions, EditorStateSnapshot, PluginCommand};
+use crate::services::plugins::hook_guard::{
+    HookAdmission, HookLoopCut, HookLoopGuard, HookLoopLimits,
+};
+use crate::services::plugins::hooks::{hook_args_to_value, HookArgs};
+use crate::services::plugins::manager::{PluginLoadError, PLUGIN_THREAD_NAME};
 use crate::services::plugins::runtime::{TsPluginInfo, TypeScriptRuntime};
 use anyhow::{anyhow, Result};
 use std::cell::RefCell;
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use std::rc::Rc;
-use std::sync::{Arc, RwLock};
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
 use std::thread::{self, JoinHandle};
 use std::time::Duration;
 
@@ -34,7 +56,7 @@ pub enum PluginRequest {
     /// Load all plugins from a directory
     LoadPluginsFromDir {
         dir: PathBuf,
-        response: oneshot::Sender<Vec<String>>,
+        response: oneshot::Sender<Vec<PluginLoadError>>,
     },
 
     /// Unload a plugin by name
@@ -52,11 +74,19 @@ pub enum PluginRequest {
     /// Execute a plugin action
     ExecuteAction {
         action_name: String,
+        options: ActionOptions,
         response: oneshot::Sender<Result<()>>,
     },
 
-    /// Run a hook (fire-and-forget, no response needed)
-    RunHook { hook_name: String, args: HookArgs },
+    /// Run a batch of hooks in order (fire-and-forget); the batch number is
+    /// stored in `hook_batches_done` once their handlers have returned
+    RunHooks {
+        hooks: Vec<(String, HookArgs)>,
+        batch: u64,
+        /// Depth of the batch in its chain of hooks
+        depth: u32,
+        limits: HookLoopLimits,
+    },
 
     /// Check if any handlers are registered for a hook
     HasHookHandlers {
@@ -142,11 +172,45 @@ pub struct PluginThreadHandle {
 
     /// Receiver for plugin commands (polled by editor directly)
     command_receiver: std::sync::mpsc::Receiver<PluginCommand>,
+
+    /// Hooks emitted since the last dispatch, in order
+    pending_hooks: Mutex<Vec<(String, HookArgs)>>,
+
+    /// Depth of the deepest hook emitted since the last dispatch
+    pending_depth: AtomicU32,
+
+    /// Depth of hooks emitted now: one deeper than the last batch sent while
+    /// the editor applies commands it may have caused, otherwise 0
+    emit_depth: AtomicU32,
+
+    /// Depth of the last hook batch sent
+    sent_depth: u32,
+
+    /// Limits of hook chains, as of the last dispatch
+    hook_loop_limits: HookLoopLimits,
+
+    /// Number of the last hook batch sent to the plugin thread
+    hook_batches_sent: u64,
+
+    /// Number of the last hook batch whose commands the editor has drained
+    hook_batches_drained: u64,
+
+    /// Number of the last hook batch the plugin thread has run (shared with it)
+    hook_batches_done: Arc<AtomicU64>,
 }
 
 impl PluginThreadHandle {
     /// Create a new plugin thread and return its handle
     pub fn spawn(commands: Arc<RwLock<CommandRegistry>>) -> Result<Self> {
+        Self::spawn_with_transpile_cache(commands, None)
+    }
+
+    /// Create a new plugin thread that caches transpiled plugins in
+    /// `transpile_cache_dir`, and return its handle
+    pub fn spawn_with_transpile_cache(
+        commands: Arc<RwLock<CommandRegistry>>,
+        transpile_cache_dir: Option<PathBuf>,
+    ) -> Result<Self> {
         tracing::debug!("PluginThreadHandle::spawn: starting plugin thread creation");
 
         // Create channel for plugin commands
@@ -166,58 +230,94 @@ impl PluginThreadHandle {
         // Clone state snapshot for the thread
         let thread_state_snapshot = Arc::clone(&state_snapshot);
         let thread_commands = Arc::clone(&commands);
+        let hook_batches_done = Arc::new(AtomicU64::new(0));
+        let thread_hook_batches_done = Arc::clone(&hook_batches_done);
+
+        // Runtime creation reports back so a failed start surfaces as an error
+        let (ready_sender, ready_receiver) = std::sync::mpsc::channel::<Result<(), String>>();
 
         // Spawn the plugin thread
         tracing::debug!("PluginThreadHandle::spawn: spawning OS thread for plugin runtime");
-        let thread_handle = thread::spawn(move || {
-            tracing::debug!("Plugin thread: OS thread started, creating tokio runtime");
-            // Create tokio runtime for the plugin thread
-            let rt = match tokio::runtime::Builder::new_current_thread()
-                .enable_all()
-                .build()
-            {
-                Ok(rt) => {
-                    tracing::debug!("Plugin thread: tokio runtime created successfully");
-                    rt
-                }
-                Err(e) => {
-                    tracing::error!("Failed to create plugin thread runtime: {}", e);
-                    return;
-                }
-            };
-
-            // Create TypeScript runtime with state
-            tracing::debug!("Plugin thread: creating TypeScript runtime (V8 initialization)");
-            let runtime = match TypeScriptRuntime::with_state_and_responses(
-                Arc::clone(&thread_state_snapshot),
-                command_sender,
-                thread_pending_responses,
-            ) {
-                Ok(rt) => {
-                    tracing::debug!("Plugin thread: TypeScript runtime created successfully");
-                    rt
-                }
-                Err(e) => {
-                    tracing::error!("Failed to create TypeScript runtime: {}", e);
-                    return;
-                }
-            };
-
-            // Create internal manager state
-            let mut plugins: HashMap<String, TsPluginInfo> = HashMap::new();
-
-            // Run the event loop with a LocalSet to allow concurrent task execution
-            tracing::debug!("Plugin thread: starting event loop with LocalSet");
-            let local = tokio::task::LocalSet::new();
-            local.block_on(&rt, async {
-                // Wrap runtime in RefCell for interior mutability during concurrent operations
-                let runtime = Rc::new(RefCell::new(runtime));
-                tracing::debug!("Plugin thread: entering plugin_thread_loop");
-                plugin_thread_loop(runtime, &mut plugins, &thread_commands, request_receiver).await;
-            });
-
-            tracing::info!("Plugin thread shutting down");
-        });
+        let thread_handle = thread::Builder::new()
+            .name(PLUGIN_THREAD_NAME.to_string())
+            .spawn(move || {
+                tracing::debug!("Plugin thread: OS thread started, creating tokio runtime");
+                // Create tokio runtime for the plugin thread
+                let rt = match tokio::runtime::Builder::new_current_thread()
+                    .enable_all()
+                    .build()
+                {
+                    Ok(rt) => {
+                        tracing::debug!("Plugin thread: tokio runtime created successfully");
+                        rt
+                    }
+                    Err(e) => {
+                        tracing::error!("Failed to create plugin thread runtime: {}", e);
+                        let _ = ready_sender.send(Err(format!("failed to create runtime: {}", e)));
+                        return;
+                    }
+                };
+
+                // Create TypeScript runtime with state. V8 may panic rather than
+                // return an error when the system doesn't let it start.
+                tracing::debug!("Plugin thread: creating TypeScript runtime (V8 initialization)");
+                let created = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                    TypeScriptRuntime::with_state_and_responses(
+                        Arc::clone(&thread_state_snapshot),
+                        command_sender,
+                        thread_pending_responses,
+                    )
+                }));
+                let runtime = match created {
+                    Ok(Ok(mut rt)) => {
+                        tracing::debug!("Plugin thread: TypeScript runtime created successfully");
+                        rt.set_transpile_cache_dir(transpile_cache_dir);
+                        rt
+                    }
+                    Ok(Err(e)) => {
+                        tracing::error!("Failed to create TypeScript runtime: {}", e);
+                        let _ = ready_sender.send(Err(e.to_string()));
+                        return;
+                    }
+                    Err(panic) => {
+                        let message = crate::v8_init::panic_message(panic.as_ref());
+                        tracing::error!("TypeScript runtime creation panicked: {}", message);
+                        let _ = ready_sender.send(Err(message));
+                        return;
+                    }
+                };
+                let _ = ready_sender.send(Ok(()));
+
+                // Create internal manager state
+                let mut plugins: HashMap<String, TsPluginInfo> = HashMap::new();
+
+                // Run the event loop with a LocalSet to allow concurrent task execution
+                tracing::debug!("Plugin thread: starting event loop with LocalSet");
+                let local = tokio::task::LocalSet::new();
+                local.block_on(&rt, async {
+                    // Wrap runtime in RefCell for interior mutability during concurrent operations
+                    let runtime = Rc::new(RefCell::new(runtime));
+                    tracing::debug!("Plugin thread: entering plugin_thread_loop");
+                    plugin_thread_loop(
+                        runtime,
+                        &mut plugins,
+                        &thread_commands,
+                        &thread_hook_batches_done,
+                        request_receiver,
+                    )
+                    .await;
+                });
+
+                tracing::info!("Plugin thread shutting down");
+            })?;
+
+        let ready = ready_receiver
+            .recv()
+            .unwrap_or_else(|_| Err("plugin thread exited during startup".to_string()));
+        if let Err(e) = ready {
+            let _ = thread_handle.join();
+            return Err(anyhow!(e));
+        }
 
         tracing::debug!("PluginThreadHandle::spawn: OS thread spawned, returning handle");
         tracing::info!("Plugin thread spawned");
@@ -229,6 +329,14 @@ impl PluginThreadHandle {
             commands,
             pending_responses,
             command_receiver,
+            pending_hooks: Mutex::new(Vec::new()),
+            pending_depth: AtomicU32::new(0),
+            emit_depth: AtomicU32::new(0),
+            sent_depth: 0,
+            hook_loop_limits: HookLoopLimits::default(),
+            hook_batches_sent: 0,
+            hook_batches_drained: 0,
+            hook_batches_done,
         })
     }
 
@@ -255,10 +363,10 @@ impl PluginThreadHandle {
     }
 
     /// Load all plugins from a directory (blocking)
-    pub fn load_plugins_from_dir(&self, dir: &Path) -> Vec<String> {
+    pub fn load_plugins_from_dir(&self, dir: &Path) -> Vec<PluginLoadError> {
         let (tx, rx) = oneshot::channel();
         let Some(sender) = self.request_sender.as_ref() else {
-            return vec!["Plugin thread shut down".to_string()];
+            return vec![PluginLoadError::general("Plugin thread shut down")];
         };
         if sender
             .send(PluginRequest::LoadPluginsFromDir {
@@ -267,11 +375,11 @@ impl PluginThreadHandle {
             })
             .is_err()
         {
-            return vec!["Plugin thread not responding".to_string()];
+            return vec![PluginLoadError::general("Plugin thread not responding")];
         }
 
         rx.recv()
-            .unwrap_or_else(|_| vec!["Plugin thread closed".to_string()])
+            .unwrap_or_else(|_| vec![PluginLoadError::general("Plugin thread closed")])
     }
 
     /// Unload a plugin (blocking)
@@ -309,6 +417,15 @@ impl PluginThreadHandle {
     /// Returns a receiver that will receive the result when the action completes.
     /// The caller should poll this while processing commands to avoid deadlock.
     pub fn execute_action_async(&self, action_name: &str) -> Result<oneshot::Receiver<Result<()>>> {
+        self.execute_action_with_options_async(action_name, ActionOptions::default())
+    }
+
+    /// Execute a plugin action (non-blocking), passing it `options`
+    pub fn execute_action_with_options_async(
+        &self,
+        action_name: &str,
+        options: ActionOptions,
+    ) -> Result<oneshot::Receiver<Result<()>>> {
         tracing::trace!("execute_action_async: starting action '{}'", action_name);
         let (tx, rx) = oneshot::channel();
         self.request_sender
@@ -316,6 +433,7 @@ impl PluginThreadHandle {
             .ok_or_else(|| anyhow!("Plugin thread shut down"))?
             .send(PluginRequest::ExecuteAction {
                 action_name: action_name.to_string(),
+                options,
                 response: tx,
             })
             .map_err(|_| anyhow!("Plugin thread not responding"))?;
@@ -326,16 +444,59 @@ impl PluginThreadHandle {
 
     /// Run a hook (non-blocking, fire-and-forget)
     ///
-    /// This is the key improvement: hooks are now non-blocking.
-    /// The plugin thread will execute them asynchronously and
-    /// any results will come back via the PluginCommand channel.
+    /// The hook is queued until the next [`Self::dispatch_hooks`]; any
+    /// results come back via the PluginCommand channel.
     pub fn run_hook(&self, hook_name: &str, args: HookArgs) {
-        if let Some(sender) = self.request_sender.as_ref() {
-            let _ = sender.send(PluginRequest::RunHook {
-                hook_name: hook_name.to_string(),
-                args,
-            });
+        self.pending_hooks
+            .lock()
+            .unwrap()
+            .push((hook_name.to_string(), args));
+        self.pending_depth
+            .fetch_max(self.emit_depth.load(Ordering::Relaxed), Ordering::Relaxed);
+    }
+
+    /// Whether hooks are queued for the next dispatch
+    pub fn has_pending_hooks(&self) -> bool {
+        !self.pending_hooks.lock().unwrap().is_empty()
+    }
+
+    /// Send the queued hooks to the plugin thread as one batch
+    ///
+    /// Does nothing while the previous batch is running or its commands have
+    /// not been drained by [`Self::process_commands`] yet; the hooks stay
+    /// queued for a later dispatch. Returns true if a batch was sent.
+    pub fn dispatch_hooks(&mut self, limits: HookLoopLimits) -> bool {
+        self.hook_loop_limits = limits;
+        if self.hook_batches_drained < self.hook_batches_sent {
+            return false;
         }
+        self.send_pending_hooks()
+    }
+
+    fn send_pending_hooks(&mut self) -> bool {
+        let hooks = std::mem::take(&mut *self.pending_hooks.lock().unwrap());
+        let depth = self.pending_depth.swap(0, Ordering::Relaxed);
+        if hooks.is_empty() {
+            return false;
+        }
+        let Some(sender) = self.request_sender.as_ref() else {
+            return false;
+        };
+        self.hook_batches_sent += 1;
+        self.sent_depth = depth;
+        tracing::trace!(
+            batch = self.hook_batches_sent,
+            hooks = hooks.len(),
+            depth,
+            "dispatching hooks"
+        );
+        let _ = sender.send(PluginRequest::RunHooks {
+            hooks,
+            batch: self.hook_batches_sent,
+            depth,
+            limits: self.hook_loop_limits,
+        });
+        true
     }
 
     /// Check if any handlers are registered for a hook (blocking)
@@ -377,14 +538,35 @@ impl PluginThreadHandle {
     ///
     /// Returns immediately with any pending commands by polling the command queue directly.
     /// This does not require the plugin thread to respond, avoiding deadlocks.
+    ///
+    /// Hooks emitted from now until [`Self::commands_applied`] count as
+    /// caused by these commands.
     pub fn process_commands(&mut self) -> Vec<PluginCommand> {
+        // Read before draining: the commands of every batch counted here are
+        // already in the channel
+        let done = self.hook_batches_done.load(Ordering::Acquire);
         let mut commands = Vec::new();
         while let Ok(cmd) = self.command_receiver.try_recv() {
             commands.push(cmd);
         }
+        // Commands drained while a batch is in flight or just done may be
+        // its handlers'
+        let depth = if self.hook_batches_drained < self.hook_batches_sent && !commands.is_empty() {
+            self.sent_depth + 1
+        } else {
+            0
+        };
+        self.emit_depth.store(depth, Ordering::Relaxed);
+        self.hook_batches_drained = done;
         commands
     }
 
+    /// The commands returned by [`Self::process_commands`] have been applied;
+    /// hooks emitted from now on start a new chain
+    pub fn commands_applied(&self) {
+        self.emit_depth.store(0, Ordering::Relaxed);
+    }
+
     /// Get the state snapshot handle for editor to update
     pub fn state_snapshot_handle(&self) -> Arc<RwLock<EditorStateSnapshot>> {
         Arc::clone(&self.state_snapshot)
@@ -413,6 +595,9 @@ impl PluginThreadHandle {
             }
         }
 
+        // Hooks still queued run before the Shutdown request
+        self.send_pending_hooks();
+
         // First send a Shutdown request to allow clean processing of pending work
         if let Some(sender) = self.request_sender.as_ref() {
             tracing::debug!("PluginThreadHandle::shutdown: sending Shutdown request");
@@ -453,6 +638,18 @@ fn respond_to_pending(
             request_id, ..
         } => *request_id,
         crate::services::plugins::api::PluginResponse::BufferText { request_id, .. } => *request_id,
+        crate::services::plugins::api::PluginResponse::PendingEditsReviewed {
+            request_id, ..
+        } => *request_id,
+        crate::services::plugins::api::PluginResponse::ActionsExecuted { request_id, .. } => {
+            *request_id
+        }
+        crate::services::plugins::api::PluginResponse::Keymap { request_id, .. } => *request_id,
+        crate::services::plugins::api::PluginResponse::LineCol { request_id, .. } => *request_id,
+        crate::services::plugins::api::PluginResponse::ByteOffset { request_id, .. } => *request_id,
+        crate::services::plugins::api::PluginResponse::BufferCreated { request_id, .. } => {
+            *request_id
+        }
     };
 
     let sender = {
@@ -536,6 +733,7 @@ async fn plugin_thread_loop(
     runtime: Rc<RefCell<TypeScriptRuntime>>,
     plugins: &mut HashMap<String, TsPluginInfo>,
     commands: &Arc<RwLock<CommandRegistry>>,
+    hook_batches_done: &AtomicU64,
     mut request_receiver: tokio::sync::mpsc::UnboundedReceiver<PluginRequest>,
 ) {
     tracing::info!("Plugin thread event loop started");
@@ -543,6 +741,7 @@ async fn plugin_thread_loop(
     // Interval for polling the JS event loop when there's pending work
     let poll_interval = Duration::from_millis(1);
     let mut has_pending_work = false;
+    let mut hook_guard = HookLoopGuard::new();
 
     loop {
         tokio::select! {
@@ -552,15 +751,24 @@ async fn plugin_thread_loop(
                 match request {
                     Some(PluginRequest::ExecuteAction {
                         action_name,
+                        options,
                         response,
                     }) => {
                         // Handle ExecuteAction specially
-                        execute_action_with_hooks(&action_name, response, Rc::clone(&runtime)).await;
+                        execute_action_with_hooks(&action_name, &options, response, Rc::clone(&runtime)).await;
                         has_pending_work = true; // Action may have started async work
                     }
                     Some(request) => {
                         let should_shutdown =
-                            handle_request(request, Rc::clone(&runtime), plugins, commands).await;
+                            handle_request(
+                                request,
+                                Rc::clone(&runtime),
+                                plugins,
+                                commands,
+                                hook_batches_done,
+                                &mut hook_guard,
+                            )
+                            .await;
 
                         if should_shutdown {
                             break;
@@ -589,6 +797,7 @@ async fn plugin_thread_loop(
 /// while the main thread is waiting for a blocking hook to complete.
 async fn execute_action_with_hooks(
     action_name: &str,
+    options: &ActionOptions,
     response: oneshot::Sender<Result<()>>,
     runtime: Rc<RefCell<TypeScriptRuntime>>,
 ) {
@@ -610,7 +819,10 @@ async fn execute_action_with_hooks(
     // waiting for hooks. But for now, we execute the action and hope for the best.
     // A proper fix requires changes to the main thread's wait_for logic.
 
-    let result = runtime.borrow_mut().execute_action(action_name).await;
+    let result = runtime
+        .borrow_mut()
+        .execute_action_with_options(action_name, options)
+        .await;
 
     tracing::trace!(
         "execute_action_with_hooks: action '{}' completed with result: {:?}",
@@ -620,39 +832,88 @@ async fn execute_action_with_hooks(
     let _ = response.send(result);
 }
 
-/// Run a hook with Rc<RefCell<TypeScriptRuntime>>
+/// Run a hook with Rc<RefCell<TypeScriptRuntime>>, skipping the handlers
+/// `hook_guard` cuts off at `depth`
 async fn run_hook_internal_rc(
     runtime: Rc<RefCell<TypeScriptRuntime>>,
     hook_name: &str,
     args: &HookArgs,
+    depth: u32,
+    limits: HookLoopLimits,
+    hook_guard: &mut HookLoopGuard,
 ) -> Result<()> {
-    // Convert HookArgs to JSON
-    let json_start = std::time::Instant::now();
-    let json_data = hook_args_to_json(args)?;
-    tracing::trace!(
-        hook = hook_name,
-        json_ms = json_start.elapsed().as_micros(),
-        "hook args serialized"
-    );
+    let handlers = runtime.borrow().get_registered_handlers(hook_name);
+    let buffer_id = args.buffer_id();
+    let handlers: Vec<_> = handlers
+        .into_iter()
+        .filter(|handler| {
+            match hook_guard.admit(&handler.plugin, hook_name, buffer_id, depth, limits) {
+                HookAdmission::Run => true,
+                HookAdmission::Cut(cut) => {
+                    report_hook_loop_cut(&runtime, cut);
+                    false
+                }
+                HookAdmission::Dropped => false,
+            }
+        })
+        .collect();
+    // Nothing to convert when no handler runs
+    if !handlers.is_empty() {
+        let json_start = std::time::Instant::now();
+        let data = hook_args_to_value(args);
+        tracing::trace!(
+            hook = hook_name,
+            json_ms = json_start.elapsed().as_micros(),
+            "hook args converted"
+        );
 
-    // Emit to TypeScript handlers
-    let emit_start = std::time::Instant::now();
-    runtime.borrow_mut().emit(hook_name, &json_data).await?;
-    tracing::trace!(
-        hook = hook_name,
-        emit_ms = emit_start.elapsed().as_millis(),
-        "emit completed"
-    );
+        // Emit to TypeScript handlers
+        let emit_start = std::time::Instant::now();
+        runtime
+            .borrow_mut()
+            .emit_value_to(hook_name, data, handlers)
+            .await?;
+        tracing::trace!(
+            hook = hook_name,
+            emit_ms = emit_start.elapsed().as_millis(),
+            "emit completed"
+        );
+    }
+
+    if let HookArgs::BufferChunk {
+        read_id,
+        done: true,
+        ..
+    } = args
+    {
+        runtime.borrow().end_buffer_chunk_read(*read_id);
+    }
 
     Ok(())
 }
 
+/// Tell the editor of a handler cut off by the hook loop guard, which warns
+/// about it
+fn report_hook_loop_cut(runtime: &Rc<RefCell<TypeScriptRuntime>>, cut: HookLoopCut) {
+    runtime
+        .borrow_mut()
+        .send_command(PluginCommand::HookLoopCut {
+            plugin: cut.plugin,
+            hook: cut.hook,
+            buffer_id: cut.buffer_id,
+            depth: cut.depth,
+            runs: cut.runs,
+        });
+}
+
 /// Handle a single request in the plugin thread
 async fn handle_request(
     request: PluginRequest,
     runtime: Rc<RefCell<TypeScriptRuntime>>,
     plugins: &mut HashMap<String, TsPluginInfo>,
     commands: &Arc<RwLock<CommandRegistry>>,
+    hook_batches_done: &AtomicU64,
+    hook_guard: &mut HookLoopGuard,
 ) -> bool {
     match request {
         PluginRequest::LoadPlugin { path, response } => {
@@ -666,7 +927,7 @@ async fn handle_request(
         }
 
         PluginRequest::UnloadPlugin { name, response } => {
-            let result = unload_plugin_internal(plugins, commands, &name);
+            let result = unload_plugin_internal(&runtime, plugins, commands, &name);
             let _ = response.send(result);
         }
 
@@ -679,6 +940,7 @@ async fn handle_request(
         PluginRequest::ExecuteAction {
             action_name,
             response,
+            ..
         } => {
             // This is handled in plugin_thread_loop with select! for concurrent processing
             // If we get here, it's an unexpected state
@@ -691,21 +953,41 @@ async fn handle_request(
             )));
         }
 
-        PluginRequest::RunHook { hook_name, args } => {
+        PluginRequest::RunHooks {
+            hooks,
+            batch,
+            depth,
+            limits,
+        } => {
             // Fire-and-forget hook execution
-            let hook_start = std::time::Instant::now();
-            tracing::trace!(hook = %hook_name, "RunHook request received");
-            if let Err(e) = run_hook_internal_rc(Rc::clone(&runtime), &hook_name, &args).await {
-                let error_msg = format!("Plugin error in '{}': {}", hook_name, e);
-                tracing::error!("{}", error_msg);
-                // Surface the error to the UI
-                runtime.borrow_mut().send_status(error_msg);
+            hook_guard.start_batch(depth);
+            for (hook_name, args) in hooks {
+                let hook_start = std::time::Instant::now();
+                tracing::trace!(hook = %hook_name, batch, depth, "RunHook request received");
+                let result = run_hook_internal_rc(
+                    Rc::clone(&runtime),
+                    &hook_name,
+                    &args,
+                    depth,
+                    limits,
+                    hook_guard,
+                )
+                .await;
+                if let Err(e) = result {
+                    let error_msg = format!("Plugin error in '{}': {}", hook_name, e);
+                    tracing::error!("{}", error_msg);
+                    // Surface the error to the UI
+                    runtime.borrow_mut().send_status(error_msg);
+                }
+                tracing::trace!(
+                    hook = %hook_name,
+                    elapsed_ms = hook_start.elapsed().as_millis(),
+                    "RunHook completed"
+                );
             }
-            tracing::trace!(
-                hook = %hook_name,
-                elapsed_ms = hook_start.elapsed().as_millis(),
-                "RunHook completed"
-            );
+            // After the commands the handlers sent, which the editor drains
+            // once it reads this
+            hook_batches_done.store(batch, Ordering::Release);
         }
 
         PluginRequest::HasHookHandlers {
@@ -767,12 +1049,15 @@ async fn load_plugin_internal(
         }
     }
 
+    let profile_phase =
+        crate::services::startup_profile::phase_with(|| format!("plugin {}", plugin_name));
     let load_start = std::time::Instant::now();
     runtime
         .borrow_mut()
         .load_module_with_source(path_str, &plugin_name)
         .await?;
     let load_elapsed = load_start.elapsed();
+    drop(profile_phase);
 
     tracing::debug!(
         "load_plugin_internal: plugin '{}' loaded successfully in {:?}",
@@ -804,7 +1089,7 @@ async fn load_plugins_from_dir_internal(
     runtime: Rc<RefCell<TypeScriptRuntime>>,
     plugins: &mut HashMap<String, TsPluginInfo>,
     dir: &Path,
-) -> Vec<String> {
+) -> Vec<PluginLoadError> {
     tracing::debug!(
         "load_plugins_from_dir_internal: scanning directory {:?}",
         dir
@@ -829,7 +1114,14 @@ async fn load_plugins_from_dir_internal(
                     );
                     if let Err(e) = load_plugin_internal(Rc::clone(&runtime), plugins, &path).await
                     {
-                        let err = format!("Failed to load {:?}: {}", path, e);
+                        let plugin = path
+                            .file_stem()
+                            .map(|stem| stem.to_string_lossy().into_owned())
+                            .unwrap_or_else(|| path.display().to_string());
+                        let err = PluginLoadError {
+                            plugin: Some(plugin),
+                            message: e.to_string(),
+                        };
                         tracing::error!("{}", err);
                         errors.push(err);
                     }
@@ -843,7 +1135,7 @@ async fn load_plugins_from_dir_internal(
             );
         }
         Err(e) => {
-            let err = format!("Failed to read plugin directory: {}", e);
+            let err = PluginLoadError::general(format!("Failed to read plugin directory: {}", e));
             tracing::error!("{}", err);
             errors.push(err);
         }
@@ -854,6 +1146,7 @@ async fn load_plugins_from_dir_internal(
 
 /// Unload a plugin
 fn unload_plugin_internal(
+    runtime: &Rc<RefCell<TypeScriptRuntime>>,
     plugins: &mut HashMap<String, TsPluginInfo>,
     commands: &Arc<RwLock<CommandRegistry>>,
     name: &str,
@@ -868,6 +1161,14 @@ fn unload_plugin_internal(
         let prefix = format!("{}:", name);
         commands.read().unwrap().unregister_by_prefix(&prefix);
 
+        // Stop its path watches, remove its gutter columns and explorer actions,
+        // finish its progress and drop its event handlers
+        runtime.borrow().unwatch_plugin_paths(name);
+        runtime.borrow().remove_plugin_gutter_columns(name);
+        runtime.borrow().remove_plugin_explorer_actions(name);
+        runtime.borrow().end_plugin_progress(name);
+        runtime.borrow().remove_plugin_handlers(name);
+
         Ok(())
     } else {
         Err(anyhow!("Plugin '{}' not found", name))
@@ -887,7 +1188,7 @@ async fn reload_plugin_internal(
         .path
         .clone();
 
-    unload_plugin_internal(plugins, commands, name)?;
+    unload_plugin_internal(&runtime, plugins, commands, name)?;
     load_plugin_internal(runtime, plugins, &path).await?;
 
     Ok(())
@@ -896,6 +1197,7 @@ async fn reload_plugin_internal(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::services::plugins::hooks::hook_args_to_json;
 
     #[test]
     fn test_oneshot_channel() {
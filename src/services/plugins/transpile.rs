@@ -0,0 +1,376 @@
+//! TypeScript transpilation for plugins
+//!
+//! Plugins are written in TypeScript and transpiled to JavaScript by deno_ast
+//! when they load. Transpiling every plugin on every start is the bulk of the
+//! plugin startup cost, so the output is kept in a cache directory, keyed by
+//! the plugin path, a hash of its source and the transpiler version. An edited
+//! plugin or an upgraded editor misses the cache and is transpiled again; the
+//! least recently used entries are pruned once the cache outgrows
+//! [`MAX_CACHE_BYTES`].
+//!
+//! A plugin that fails to parse reports a [`TranspileError`] with the file,
+//! line, column and the offending source line, instead of a bare parser
+//! message. The source map of every transpiled module is kept so that stack
+//! traces point at the TypeScript lines.
+
+use deno_core::ModuleSpecifier;
+use serde::{Deserialize, Serialize};
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// Size the transpile cache is pruned down to
+pub const MAX_CACHE_BYTES: u64 = 32 * 1024 * 1024;
+
+/// Part of the cache key, so a different transpiler never reuses old output
+const TRANSPILER_VERSION: &str = concat!("deno_ast-0.51/fresh-", env!("CARGO_PKG_VERSION"));
+
+/// A plugin that couldn't be read or transpiled
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TranspileError {
+    /// The plugin file
+    pub path: PathBuf,
+    /// 1-based line of the error, 0 when it has no position
+    pub line: usize,
+    /// 1-based column of the error, 0 when it has no position
+    pub column: usize,
+    pub message: String,
+    /// The source line the error is on
+    pub source_line: Option<String>,
+}
+
+impl TranspileError {
+    /// An error at `line`:`column` of `source`
+    fn at(path: &Path, source: &str, line: usize, column: usize, message: String) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            line,
+            column,
+            message,
+            source_line: line
+                .checked_sub(1)
+                .and_then(|index| source.lines().nth(index))
+                .map(str::to_string),
+        }
+    }
+
+    /// An error that isn't tied to a position in the file
+    fn whole_file(path: &Path, message: String) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            line: 0,
+            column: 0,
+            message,
+            source_line: None,
+        }
+    }
+}
+
+impl fmt::Display for TranspileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.line == 0 {
+            return write!(f, "{}: {}", self.path.display(), self.message);
+        }
+        write!(
+            f,
+            "{}:{}:{}: {}",
+            self.path.display(),
+            self.line,
+            self.column,
+            self.message
+        )?;
+        if let Some(source_line) = &self.source_line {
+            let gutter = self.line.to_string();
+            // Keep tabs so the caret lines up however wide they render
+            let indent: String = source_line
+                .chars()
+                .take(self.column.saturating_sub(1))
+                .map(|c| if c == '\t' { '\t' } else { ' ' })
+                .collect();
+            write!(
+                f,
+                "\n{} | {}\n{} | {}^",
+                gutter,
+                source_line,
+                " ".repeat(gutter.len()),
+                indent
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for TranspileError {}
+
+/// Transpiled code and its source map, as stored in the cache
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Transpiled {
+    code: String,
+    source_map: Option<String>,
+}
+
+/// Transpiles plugin modules, through the cache when there is one
+///
+/// Shared between the runtime, which transpiles a plugin before loading it
+/// to report errors with their location, and the module loader, which hands
+/// the code to V8 and looks up source maps for stack traces.
+#[derive(Default)]
+pub struct Transpiler {
+    cache_dir: RefCell<Option<PathBuf>>,
+    /// Code transpiled ahead of loading, by module specifier
+    prepared: RefCell<HashMap<String, String>>,
+    /// Source maps of the loaded modules, by module specifier
+    source_maps: RefCell<HashMap<String, Vec<u8>>>,
+    /// Modules transpiled rather than taken from the cache
+    transpile_count: Cell<usize>,
+}
+
+impl Transpiler {
+    /// Keep transpiled output in `dir` (None to transpile every time)
+    pub fn set_cache_dir(&self, dir: Option<PathBuf>) {
+        *self.cache_dir.borrow_mut() = dir;
+    }
+
+    /// How many modules were transpiled rather than taken from the cache
+    pub fn transpile_count(&self) -> usize {
+        self.transpile_count.get()
+    }
+
+    /// Transpile the module at `path` ahead of loading it, so that errors
+    /// come back as they are rather than through the module loader
+    pub fn prepare(&self, specifier: &ModuleSpecifier, path: &Path) -> Result<(), TranspileError> {
+        let code = self.transpile_file(specifier, path)?;
+        self.prepared
+            .borrow_mut()
+            .insert(specifier.to_string(), code);
+        Ok(())
+    }
+
+    /// Code transpiled by [`Self::prepare`] for `specifier`
+    pub fn take_prepared(&self, specifier: &ModuleSpecifier) -> Option<String> {
+        self.prepared.borrow_mut().remove(specifier.as_str())
+    }
+
+    /// Source map of a loaded module
+    pub fn source_map(&self, specifier: &str) -> Option<Vec<u8>> {
+        self.source_maps.borrow().get(specifier).cloned()
+    }
+
+    /// Read and transpile the TypeScript module at `path`
+    pub fn transpile_file(
+        &self,
+        specifier: &ModuleSpecifier,
+        path: &Path,
+    ) -> Result<String, TranspileError> {
+        let source = std::fs::read_to_string(path)
+            .map_err(|e| TranspileError::whole_file(path, format!("failed to read: {}", e)))?;
+        let cache_path = self
+            .cache_dir
+            .borrow()
+            .as_ref()
+            .map(|dir| dir.join(format!("{}.json", cache_key(path, &source))));
+
+        let transpiled = match cache_path.as_deref().and_then(read_cached) {
+            Some(cached) => cached,
+            None => {
+                let transpiled = transpile(&source, specifier, path)?;
+                self.transpile_count.set(self.transpile_count.get() + 1);
+                if let Some(cache_path) = &cache_path {
+                    write_cached(cache_path, &transpiled);
+                }
+                transpiled
+            }
+        };
+
+        if let Some(source_map) = transpiled.source_map {
+            self.source_maps
+                .borrow_mut()
+                .insert(specifier.to_string(), source_map.into_bytes());
+        }
+        Ok(transpiled.code)
+    }
+}
+
+/// Transpile TypeScript to JavaScript using deno_ast
+fn transpile(
+    source: &str,
+    specifier: &ModuleSpecifier,
+    path: &Path,
+) -> Result<Transpiled, TranspileError> {
+    use deno_ast::{EmitOptions, MediaType, ParseParams, SourceMapOption, TranspileOptions};
+
+    let parsed = deno_ast::parse_module(ParseParams {
+        specifier: specifier.clone(),
+        text: source.into(),
+        media_type: MediaType::TypeScript,
+        capture_tokens: false,
+        scope_analysis: false,
+        maybe_syntax: None,
+    })
+    .map_err(|diagnostic| {
+        let position = diagnostic.display_position();
+        TranspileError::at(
+            path,
+            source,
+            position.line_number,
+            position.column_number,
+            diagnostic.message().to_string(),
+        )
+    })?;
+
+    let emitted = parsed
+        .transpile(
+            &TranspileOptions::default(),
+            &Default::default(),
+            &EmitOptions {
+                source_map: SourceMapOption::Separate,
+                ..Default::default()
+            },
+        )
+        .map_err(|e| TranspileError::whole_file(path, e.to_string()))?
+        .into_source();
+
+    Ok(Transpiled {
+        code: emitted.text,
+        source_map: emitted.source_map,
+    })
+}
+
+/// Cache file name for `source` at `path`
+fn cache_key(path: &Path, source: &str) -> String {
+    use sha2::{Digest, Sha256};
+    let mut hasher = Sha256::new();
+    hasher.update(TRANSPILER_VERSION.as_bytes());
+    hasher.update([0]);
+    hasher.update(path.to_string_lossy().as_bytes());
+    hasher.update([0]);
+    hasher.update(source.as_bytes());
+    format!("{:x}", hasher.finalize())
+}
+
+fn read_cached(cache_path: &Path) -> Option<Transpiled> {
+    let content = std::fs::read_to_string(cache_path).ok()?;
+    let transpiled = serde_json::from_str(&content).ok()?;
+    // Mark the entry as used so pruning keeps it
+    if let Ok(file) = std::fs::File::options().append(true).open(cache_path) {
+        let _ = file.set_modified(SystemTime::now());
+    }
+    Some(transpiled)
+}
+
+/// Store an entry, pruning the cache if it grew too large. A cache that
+/// can't be written only costs the next start a transpile, and a torn entry
+/// fails to parse and is transpiled again.
+fn write_cached(cache_path: &Path, transpiled: &Transpiled) {
+    let Some(dir) = cache_path.parent() else {
+        return;
+    };
+    let written = serde_json::to_string(transpiled)
+        .map_err(std::io::Error::other)
+        .and_then(|content| {
+            std::fs::create_dir_all(dir)?;
+            std::fs::write(cache_path, content)
+        });
+    match written {
+        Ok(()) => prune_cache(dir, MAX_CACHE_BYTES),
+        Err(e) => tracing::debug!("Failed to cache transpiled plugin {:?}: {}", cache_path, e),
+    }
+}
+
+/// Delete the least recently used entries until the cache fits in `max_bytes`
+fn prune_cache(dir: &Path, max_bytes: u64) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    let mut files: Vec<(SystemTime, u64, PathBuf)> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let metadata = entry.metadata().ok()?;
+            let modified = metadata.modified().ok()?;
+            metadata
+                .is_file()
+                .then(|| (modified, metadata.len(), entry.path()))
+        })
+        .collect();
+    let mut total: u64 = files.iter().map(|(_, len, _)| len).sum();
+    files.sort();
+    for (_, len, path) in files {
+        if total <= max_bytes {
+            break;
+        }
+        if std::fs::remove_file(&path).is_ok() {
+            total -= len;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn specifier(path: &Path) -> ModuleSpecifier {
+        ModuleSpecifier::from_file_path(path).unwrap()
+    }
+
+    #[test]
+    fn test_syntax_error_has_location_and_caret() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("broken.ts");
+        std::fs::write(&path, "const ok = 1;\nconst broken = (;\n").unwrap();
+
+        let error = Transpiler::default()
+            .transpile_file(&specifier(&path), &path)
+            .unwrap_err();
+        assert_eq!(error.line, 2);
+        assert_eq!(error.source_line.as_deref(), Some("const broken = (;"));
+        let text = error.to_string();
+        let expected_head = format!("{}:2:{}: ", path.display(), error.column);
+        assert!(text.starts_with(&expected_head), "{text}");
+        let caret_line = text.lines().last().unwrap();
+        assert_eq!(caret_line.find('^'), Some(4 + error.column - 1), "{text}");
+    }
+
+    #[test]
+    fn test_cache_hit_skips_transpile_and_edit_misses() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("plugin.ts");
+        std::fs::write(&path, "const x: number = 1;\n").unwrap();
+        let cache_dir = temp_dir.path().join("cache");
+
+        let cold = Transpiler::default();
+        cold.set_cache_dir(Some(cache_dir.clone()));
+        let code = cold.transpile_file(&specifier(&path), &path).unwrap();
+        assert_eq!(cold.transpile_count(), 1);
+
+        let warm = Transpiler::default();
+        warm.set_cache_dir(Some(cache_dir.clone()));
+        assert_eq!(warm.transpile_file(&specifier(&path), &path).unwrap(), code);
+        assert_eq!(warm.transpile_count(), 0);
+        assert!(warm.source_map(specifier(&path).as_str()).is_some());
+
+        std::fs::write(&path, "const x: number = 2;\n").unwrap();
+        warm.transpile_file(&specifier(&path), &path).unwrap();
+        assert_eq!(warm.transpile_count(), 1);
+    }
+
+    #[test]
+    fn test_prune_cache_drops_oldest_entries() {
+        let temp_dir = TempDir::new().unwrap();
+        let now = SystemTime::now();
+        for (index, name) in ["old", "middle", "new"].iter().enumerate() {
+            let path = temp_dir.path().join(format!("{name}.json"));
+            std::fs::write(&path, [b'x'; 100]).unwrap();
+            let file = std::fs::File::options().append(true).open(&path).unwrap();
+            let age = std::time::Duration::from_secs(60 * (3 - index as u64));
+            file.set_modified(now - age).unwrap();
+        }
+
+        prune_cache(temp_dir.path(), 200);
+        assert!(!temp_dir.path().join("old.json").exists());
+        assert!(temp_dir.path().join("middle.json").exists());
+        assert!(temp_dir.path().join("new.json").exists());
+    }
+}
@@ -0,0 +1,179 @@
+//! Recently used project directories
+//!
+//! Every start and every project switch moves the working directory to the
+//! front of `recent_projects.json` in the state directory, which "Open Recent
+//! Project" lists. Only the [`MAX_RECENT_PROJECTS`] most recent entries are
+//! kept, not counting pinned projects: those are listed first and stay until
+//! unpinned.
+
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+/// Unpinned projects kept in the list
+pub const MAX_RECENT_PROJECTS: usize = 20;
+
+/// A project directory in the recent list
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecentProject {
+    /// Canonical path of the project directory
+    pub path: PathBuf,
+    /// When the project was last opened, in Unix seconds
+    pub last_opened: u64,
+    /// Listed first and never dropped from the list
+    #[serde(default)]
+    pub pinned: bool,
+}
+
+/// Contents of `recent_projects.json`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecentProjects {
+    /// Most recently opened first
+    #[serde(default)]
+    pub projects: Vec<RecentProject>,
+}
+
+impl RecentProjects {
+    /// Read the list from `path`; a missing or unreadable file gives an empty list
+    pub fn load(path: &Path) -> Self {
+        match std::fs::read_to_string(path) {
+            Ok(json) => serde_json::from_str(&json).unwrap_or_else(|e| {
+                tracing::warn!("Ignoring invalid recent projects file {:?}: {}", path, e);
+                Self::default()
+            }),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Self::default(),
+            Err(e) => {
+                tracing::warn!("Failed to read recent projects file {:?}: {}", path, e);
+                Self::default()
+            }
+        }
+    }
+
+    /// Write the list to `path`, creating its directory if needed
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let json = serde_json::to_string_pretty(self).map_err(io::Error::other)?;
+        std::fs::write(path, json)
+    }
+
+    /// Move `dir` to the front, opened at `now` (Unix seconds), dropping the
+    /// oldest unpinned projects past [`MAX_RECENT_PROJECTS`]
+    pub fn record(&mut self, dir: &Path, now: u64) {
+        let dir = canonical(dir);
+        let pinned = match self.projects.iter().position(|p| p.path == dir) {
+            Some(index) => self.projects.remove(index).pinned,
+            None => false,
+        };
+        self.projects.insert(
+            0,
+            RecentProject {
+                path: dir,
+                last_opened: now,
+                pinned,
+            },
+        );
+
+        let mut unpinned = 0;
+        self.projects.retain(|project| {
+            if project.pinned {
+                return true;
+            }
+            unpinned += 1;
+            unpinned <= MAX_RECENT_PROJECTS
+        });
+    }
+
+    /// The entry for `dir`, if it is in the list
+    pub fn get(&self, dir: &Path) -> Option<&RecentProject> {
+        let dir = canonical(dir);
+        self.projects.iter().find(|p| p.path == dir)
+    }
+
+    /// Pin or unpin `dir`; returns false if it isn't in the list
+    pub fn set_pinned(&mut self, dir: &Path, pinned: bool) -> bool {
+        let dir = canonical(dir);
+        match self.projects.iter_mut().find(|p| p.path == dir) {
+            Some(project) => {
+                project.pinned = pinned;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Drop `dir` from the list; returns false if it wasn't there
+    pub fn remove(&mut self, dir: &Path) -> bool {
+        let len = self.projects.len();
+        self.projects.retain(|p| p.path != dir);
+        self.projects.len() != len
+    }
+
+    /// Projects in the order they are offered: pinned first, then the rest,
+    /// each most recently opened first
+    pub fn ordered(&self) -> Vec<&RecentProject> {
+        let mut projects: Vec<&RecentProject> = self.projects.iter().collect();
+        projects.sort_by_key(|p| !p.pinned);
+        projects
+    }
+}
+
+/// Resolve symlinks so one directory isn't listed twice; directories that no
+/// longer exist are kept as given
+fn canonical(dir: &Path) -> PathBuf {
+    dir.canonicalize().unwrap_or_else(|_| dir.to_path_buf())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_record_moves_project_to_front_without_duplicates() {
+        let mut recent = RecentProjects::default();
+        recent.record(Path::new("/no/such/a"), 1);
+        recent.record(Path::new("/no/such/b"), 2);
+        recent.record(Path::new("/no/such/a"), 3);
+
+        let paths: Vec<&Path> = recent.projects.iter().map(|p| p.path.as_path()).collect();
+        assert_eq!(paths, [Path::new("/no/such/a"), Path::new("/no/such/b")]);
+        assert_eq!(recent.projects[0].last_opened, 3);
+    }
+
+    #[test]
+    fn test_record_caps_unpinned_projects_only() {
+        let mut recent = RecentProjects::default();
+        recent.record(Path::new("/no/such/pinned"), 0);
+        assert!(recent.set_pinned(Path::new("/no/such/pinned"), true));
+        for i in 0..MAX_RECENT_PROJECTS + 5 {
+            recent.record(Path::new(&format!("/no/such/{}", i)), i as u64 + 1);
+        }
+
+        assert_eq!(recent.projects.len(), MAX_RECENT_PROJECTS + 1);
+        assert!(recent.get(Path::new("/no/such/pinned")).is_some());
+        assert!(recent.get(Path::new("/no/such/0")).is_none());
+
+        // Pinned projects come first, and recording keeps the pin
+        assert_eq!(recent.ordered()[0].path, Path::new("/no/such/pinned"));
+        recent.record(Path::new("/no/such/pinned"), 100);
+        assert!(recent.get(Path::new("/no/such/pinned")).unwrap().pinned);
+    }
+
+    #[test]
+    fn test_save_and_load_round_trip() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("state").join("recent_projects.json");
+        assert_eq!(RecentProjects::load(&path), RecentProjects::default());
+
+        let mut recent = RecentProjects::default();
+        recent.record(temp.path(), 42);
+        recent.save(&path).unwrap();
+        assert_eq!(RecentProjects::load(&path), recent);
+
+        assert!(recent.remove(&temp.path().canonicalize().unwrap()));
+        assert!(recent.projects.is_empty());
+    }
+}
@@ -13,13 +13,19 @@
 //!
 //! ## File Layout
 //!
+//! Each project (canonical working directory) has a bucket of its own, so
+//! recovering after a crash only brings back that project's buffers. Sessions
+//! without a project use the global bucket.
+//!
 //! ```text
 //! ~/.local/share/fresh/recovery/
-//! ├── session.lock           # Session info (PID, start time)
-//! ├── {hash}.meta.json       # Recovery metadata with chunk index
-//! ├── {hash}.chunk.0         # Chunk 0 binary content
-//! ├── {hash}.chunk.1         # Chunk 1 binary content
-//! └── ...
+//! ├── projects/{dir hash}/
+//! │   ├── session.lock       # Session info (PID, start time)
+//! │   ├── {hash}.meta.json   # Recovery metadata with chunk index
+//! │   ├── {hash}.chunk.{gen}.0  # Chunk 0 binary content
+//! │   ├── {hash}.chunk.{gen}.1  # Chunk 1 binary content
+//! │   └── ...
+//! └── global/                # Same layout, for sessions without a project
 //! ```
 //!
 //! ## Storage Format
@@ -28,6 +34,12 @@
 //! - For small files/new buffers: single chunk containing full content
 //! - For large files: only modified regions stored as chunks
 //!
+//! Every buffer has files of its own, and only buffers modified since their
+//! last recovery save are written again. A save writes a new generation of
+//! chunks before switching the metadata to it, so a crash part way through
+//! one buffer's save leaves its previous save, and every other buffer's,
+//! recoverable.
+//!
 //! ## Usage
 //!
 //! ```rust,ignore
@@ -55,7 +67,8 @@ pub mod types;
 pub use storage::RecoveryStorage;
 pub use types::{
     generate_buffer_id, path_hash, ChunkMeta, ChunkedRecoveryData, ChunkedRecoveryIndex,
-    RecoveryChunk, RecoveryEntry, RecoveryMetadata, RecoveryResult, SessionInfo, MAX_CHUNK_SIZE,
+    RecoveryBucket, RecoveryChunk, RecoveryEntry, RecoveryMetadata, RecoveryResult, SessionInfo,
+    MAX_CHUNK_SIZE,
 };
 
 use std::collections::HashMap;
@@ -92,6 +105,9 @@ impl Default for RecoveryConfig {
 pub struct RecoveryService {
     /// Storage backend
     storage: RecoveryStorage,
+    /// The global bucket, when `storage` is a project's bucket: untitled buffers
+    /// left there by crashed sessions without a project are recovered too
+    global: Option<RecoveryStorage>,
     /// Configuration
     config: RecoveryConfig,
     /// Last auto-save time per buffer
@@ -105,6 +121,7 @@ impl RecoveryService {
     pub fn new() -> io::Result<Self> {
         Ok(Self {
             storage: RecoveryStorage::new()?,
+            global: None,
             config: RecoveryConfig::default(),
             last_save_times: HashMap::new(),
             session_started: false,
@@ -115,6 +132,7 @@ impl RecoveryService {
     pub fn with_config(config: RecoveryConfig) -> io::Result<Self> {
         Ok(Self {
             storage: RecoveryStorage::new()?,
+            global: None,
             config,
             last_save_times: HashMap::new(),
             session_started: false,
@@ -126,6 +144,7 @@ impl RecoveryService {
     pub fn with_storage_dir(storage_dir: PathBuf) -> Self {
         Self {
             storage: RecoveryStorage::with_dir(storage_dir),
+            global: None,
             config: RecoveryConfig::default(),
             last_save_times: HashMap::new(),
             session_started: false,
@@ -136,6 +155,41 @@ impl RecoveryService {
     pub fn with_config_and_dir(config: RecoveryConfig, storage_dir: PathBuf) -> Self {
         Self {
             storage: RecoveryStorage::with_dir(storage_dir),
+            global: None,
+            config,
+            last_save_times: HashMap::new(),
+            session_started: false,
+        }
+    }
+
+    /// Create a recovery service for a session in `working_dir`
+    ///
+    /// Recovery files go to the project's bucket under `recovery_root`, or to the
+    /// global bucket when there is no working directory. Files in the flat layout
+    /// of earlier versions are migrated first.
+    pub fn for_working_dir(
+        config: RecoveryConfig,
+        recovery_root: PathBuf,
+        working_dir: Option<&Path>,
+    ) -> Self {
+        if config.enabled {
+            if let Err(e) = RecoveryStorage::migrate_legacy_layout(&recovery_root) {
+                tracing::warn!("Failed to migrate legacy recovery files: {}", e);
+            }
+        }
+
+        let global = RecoveryStorage::with_dir(RecoveryBucket::Global.dir(&recovery_root));
+        let (storage, global) = match working_dir {
+            Some(dir) => (
+                RecoveryStorage::with_dir(RecoveryBucket::for_working_dir(dir).dir(&recovery_root)),
+                Some(global),
+            ),
+            None => (global, None),
+        };
+
+        Self {
+            storage,
+            global,
             config,
             last_save_times: HashMap::new(),
             session_started: false,
@@ -166,10 +220,35 @@ impl RecoveryService {
         if self.storage.detect_crash()? {
             // Also check if there are any recovery files
             let entries = self.storage.list_entries()?;
-            return Ok(!entries.is_empty());
+            if !entries.is_empty() {
+                return Ok(true);
+            }
         }
 
-        Ok(false)
+        Ok(!self.abandoned_untitled_entries()?.is_empty())
+    }
+
+    /// Untitled buffers in the global bucket whose session crashed
+    fn abandoned_untitled_entries(&self) -> io::Result<Vec<RecoveryEntry>> {
+        let Some(global) = &self.global else {
+            return Ok(Vec::new());
+        };
+        if !global.detect_crash()? {
+            return Ok(Vec::new());
+        }
+        Ok(global
+            .list_entries()?
+            .into_iter()
+            .filter(|entry| entry.metadata.original_path.is_none())
+            .collect())
+    }
+
+    /// The storage an entry was listed from
+    fn storage_for(&self, entry: &RecoveryEntry) -> &RecoveryStorage {
+        match &self.global {
+            Some(global) if entry.metadata_path.starts_with(global.base_dir()) => global,
+            _ => &self.storage,
+        }
     }
 
     /// Start a new session (call on editor startup after recovery handling)
@@ -216,9 +295,10 @@ impl RecoveryService {
     /// Check if a buffer needs auto-save
     ///
     /// Returns true if recovery_pending is true AND enough time has passed since
-    /// the last recovery save. The recovery_pending flag is now tracked on the
-    /// buffer itself (TextBuffer.recovery_pending) rather than in this service.
-    pub fn needs_auto_save(&self, buffer_id: &str, recovery_pending: bool) -> bool {
+    /// the last recovery save, as of `now`. The recovery_pending flag is now
+    /// tracked on the buffer itself (TextBuffer.recovery_pending) rather than
+    /// in this service.
+    pub fn needs_auto_save(&self, buffer_id: &str, recovery_pending: bool, now: Instant) -> bool {
         if !self.config.enabled {
             return false;
         }
@@ -230,7 +310,7 @@ impl RecoveryService {
 
         let interval = Duration::from_secs(self.config.auto_save_interval_secs as u64);
         match self.last_save_times.get(buffer_id) {
-            Some(last_time) => last_time.elapsed() >= interval,
+            Some(last_time) => now.saturating_duration_since(*last_time) >= interval,
             None => true, // Never saved, needs save
         }
     }
@@ -295,6 +375,12 @@ impl RecoveryService {
         Ok(())
     }
 
+    /// Record that recovery for a buffer is being written elsewhere, through
+    /// a clone of [`Self::storage`], at `now`
+    pub fn record_save(&mut self, buffer_id: &str, now: Instant) {
+        self.last_save_times.insert(buffer_id.to_string(), now);
+    }
+
     /// Delete recovery for a buffer (call when buffer is saved normally or closed)
     pub fn delete_buffer_recovery(&mut self, buffer_id: &str) -> io::Result<()> {
         if !self.config.enabled {
@@ -308,9 +394,13 @@ impl RecoveryService {
         Ok(())
     }
 
-    /// List all recoverable entries
+    /// List all recoverable entries: this session's bucket, plus untitled buffers
+    /// left in the global bucket by a crashed session without a project
     pub fn list_recoverable(&self) -> io::Result<Vec<RecoveryEntry>> {
-        self.storage.list_entries()
+        let mut entries = self.storage.list_entries()?;
+        entries.extend(self.abandoned_untitled_entries()?);
+        entries.sort_by(|a, b| b.metadata.updated_at.cmp(&a.metadata.updated_at));
+        Ok(entries)
     }
 
     /// Load recovery content for a specific entry
@@ -319,6 +409,8 @@ impl RecoveryService {
     /// can apply chunks directly to the buffer (more efficient than full reconstruction).
     /// For new buffer entries (original_file_size == 0), the full content is in the chunks.
     pub fn load_recovery(&self, entry: &RecoveryEntry) -> io::Result<RecoveryResult> {
+        let storage = self.storage_for(entry);
+
         // Check if we need the original file for reconstruction
         if entry.metadata.original_file_size > 0 {
             // Large file recovery - return chunks to apply on top of original
@@ -342,12 +434,9 @@ impl RecoveryService {
                 }
 
                 // Load chunks and return them for direct application
-                let chunked_data =
-                    self.storage
-                        .read_chunked_content(&entry.id)?
-                        .ok_or_else(|| {
-                            io::Error::new(io::ErrorKind::NotFound, "Chunk content not found")
-                        })?;
+                let chunked_data = storage.read_chunked_content(&entry.id)?.ok_or_else(|| {
+                    io::Error::new(io::ErrorKind::NotFound, "Chunk content not found")
+                })?;
 
                 return Ok(RecoveryResult::RecoveredChunks {
                     original_path: original_path.clone(),
@@ -363,8 +452,7 @@ impl RecoveryService {
 
         // New buffer or small file - chunk contains full content
         // Load the chunk data directly
-        let chunked_data = self
-            .storage
+        let chunked_data = storage
             .read_chunked_content(&entry.id)?
             .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "Chunk content not found"))?;
 
@@ -391,7 +479,7 @@ impl RecoveryService {
         original_file: &Path,
     ) -> io::Result<RecoveryResult> {
         let content = self
-            .storage
+            .storage_for(entry)
             .reconstruct_from_chunks(&entry.id, original_file)?;
         Ok(RecoveryResult::Recovered {
             original_path: Some(original_file.to_path_buf()),
@@ -404,19 +492,27 @@ impl RecoveryService {
         let result = self.load_recovery(entry)?;
         // Delete the recovery file after successful load
         if matches!(result, RecoveryResult::Recovered { .. }) {
-            self.storage.delete_recovery(&entry.id)?;
+            self.storage_for(entry).delete_recovery(&entry.id)?;
         }
         Ok(result)
     }
 
     /// Discard recovery for an entry
     pub fn discard_recovery(&mut self, entry: &RecoveryEntry) -> io::Result<()> {
-        self.storage.delete_recovery(&entry.id)
+        self.storage_for(entry).delete_recovery(&entry.id)
     }
 
     /// Discard all recovery files
     pub fn discard_all_recovery(&mut self) -> io::Result<usize> {
-        self.storage.cleanup_all()
+        let mut discarded = self.storage.cleanup_all()?;
+        if let Some(global) = &self.global {
+            for entry in self.abandoned_untitled_entries()? {
+                if global.delete_recovery(&entry.id).is_ok() {
+                    discarded += 1;
+                }
+            }
+        }
+        Ok(discarded)
     }
 
     // ========================================================================
@@ -457,6 +553,7 @@ impl Default for RecoveryService {
     fn default() -> Self {
         Self::new().unwrap_or_else(|_| Self {
             storage: RecoveryStorage::default(),
+            global: None,
             config: RecoveryConfig::default(),
             last_save_times: HashMap::new(),
             session_started: false,
@@ -474,6 +571,7 @@ mod tests {
         let storage = RecoveryStorage::with_dir(temp_dir.path().to_path_buf());
         let service = RecoveryService {
             storage,
+            global: None,
             config: RecoveryConfig::default(),
             last_save_times: HashMap::new(),
             session_started: false,
@@ -528,6 +626,57 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_project_bucket_includes_abandoned_global_untitled() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path().join("recovery");
+        let project = temp.path().join("project");
+        std::fs::create_dir_all(&project).unwrap();
+
+        // A session without a project left an untitled buffer and a file buffer
+        let global = RecoveryStorage::with_dir(RecoveryBucket::Global.dir(&root));
+        let chunks = vec![RecoveryChunk::new(0, 0, b"notes".to_vec())];
+        global
+            .save_recovery("untitled", chunks, None, Some("Untitled-1"), None, 0, 5)
+            .unwrap();
+        let chunks = vec![RecoveryChunk::new(0, 0, b"file".to_vec())];
+        let path = Path::new("/elsewhere/file.txt");
+        global
+            .save_recovery("file", chunks, Some(path), None, None, 0, 4)
+            .unwrap();
+
+        let service = RecoveryService::for_working_dir(
+            RecoveryConfig::default(),
+            root.clone(),
+            Some(&project),
+        );
+
+        // Not offered while that session may still be running
+        assert!(service.list_recoverable().unwrap().is_empty());
+
+        // Once it's gone, only its untitled buffer is offered
+        let info = SessionInfo {
+            pid: u32::MAX / 2,
+            ..SessionInfo::new()
+        };
+        std::fs::write(
+            RecoveryBucket::Global.dir(&root).join("session.lock"),
+            serde_json::to_string(&info).unwrap(),
+        )
+        .unwrap();
+        assert!(service.should_offer_recovery().unwrap());
+        let entries = service.list_recoverable().unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].id, "untitled");
+
+        // Recovering it deletes it from the global bucket
+        let mut service = service;
+        let result = service.accept_recovery(&entries[0]).unwrap();
+        assert!(matches!(result, RecoveryResult::Recovered { .. }));
+        assert!(global.load_entry("untitled").unwrap().is_none());
+        assert!(!service.should_offer_recovery().unwrap());
+    }
+
     #[test]
     fn test_needs_auto_save() {
         let (service, _temp) = create_test_service();
@@ -537,17 +686,17 @@ mod tests {
 
         let id = "test-buffer";
 
+        let now = Instant::now();
+
         // Not recovery_pending - doesn't need save
-        assert!(!service.needs_auto_save(id, false));
+        assert!(!service.needs_auto_save(id, false, now));
 
         // recovery_pending=true - needs save
-        assert!(service.needs_auto_save(id, true));
+        assert!(service.needs_auto_save(id, true, now));
 
         // After save, recovery_pending would be false on buffer
-        service
-            .last_save_times
-            .insert(id.to_string(), Instant::now());
-        assert!(!service.needs_auto_save(id, false));
+        service.record_save(id, now);
+        assert!(!service.needs_auto_save(id, false, now));
     }
 
     #[test]
@@ -556,7 +705,7 @@ mod tests {
         service.config.enabled = false;
 
         // needs_auto_save returns false when disabled
-        assert!(!service.needs_auto_save("test", true));
+        assert!(!service.needs_auto_save("test", true, Instant::now()));
 
         // save_buffer doesn't error when disabled
         let chunks = vec![RecoveryChunk::new(0, 0, b"content".to_vec())];
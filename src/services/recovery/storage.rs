@@ -4,10 +4,10 @@
 //! to ensure data integrity even during crashes.
 
 use super::types::{
-    generate_buffer_id, path_hash, ChunkedRecoveryData, ChunkedRecoveryIndex, RecoveryChunk,
-    RecoveryEntry, RecoveryMetadata, SessionInfo,
+    generate_buffer_id, path_hash, ChunkedRecoveryData, ChunkedRecoveryIndex, RecoveryBucket,
+    RecoveryChunk, RecoveryEntry, RecoveryMetadata, SessionInfo,
 };
-use crate::input::input_history::get_data_dir;
+use crate::config_io::DirectoryContext;
 use std::fs::{self, File};
 use std::io::{self, Write};
 use std::path::{Path, PathBuf};
@@ -16,7 +16,7 @@ use std::time::SystemTime;
 /// Recovery storage manager
 ///
 /// Handles all file I/O for the recovery system with atomic operations.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct RecoveryStorage {
     /// Base directory for recovery files
     recovery_dir: PathBuf,
@@ -43,8 +43,62 @@ impl RecoveryStorage {
 
     /// Get the recovery directory path
     pub fn get_recovery_dir() -> io::Result<PathBuf> {
-        let data_dir = get_data_dir()?;
-        Ok(data_dir.join("recovery"))
+        Ok(DirectoryContext::from_system(false)?.recovery_dir())
+    }
+
+    /// Move recovery files from the flat layout used before per-project buckets
+    /// into the bucket of the session that wrote them.
+    ///
+    /// The old session lock records that session's working directory; without
+    /// one, the files go to the global bucket. Returns the number of files moved.
+    ///
+    /// TODO: Remove once the flat layout is a release behind.
+    pub fn migrate_legacy_layout(recovery_root: &Path) -> io::Result<usize> {
+        if !recovery_root.is_dir() {
+            return Ok(0);
+        }
+
+        let files: Vec<PathBuf> = fs::read_dir(recovery_root)?
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| path.is_file())
+            .collect();
+        if files.is_empty() {
+            return Ok(0);
+        }
+
+        let legacy = Self::with_dir(recovery_root.to_path_buf());
+        let bucket = match legacy.read_session_lock() {
+            Ok(Some(SessionInfo {
+                working_dir: Some(dir),
+                ..
+            })) => RecoveryBucket::for_working_dir(&dir),
+            _ => RecoveryBucket::Global,
+        };
+        let target = bucket.dir(recovery_root);
+        fs::create_dir_all(&target)?;
+
+        let mut moved = 0;
+        for path in files {
+            let Some(name) = path.file_name() else {
+                continue;
+            };
+            let dest = target.join(name);
+            if dest.exists() {
+                // The bucket's own file is newer than the legacy one
+                let _ = fs::remove_file(&path);
+                continue;
+            }
+            fs::rename(&path, &dest)?;
+            moved += 1;
+        }
+
+        tracing::info!(
+            "Migrated {} legacy recovery files to {}",
+            moved,
+            target.display()
+        );
+        Ok(moved)
     }
 
     /// Ensure the recovery directory exists
@@ -142,10 +196,14 @@ impl RecoveryStorage {
         (meta_path, content_path)
     }
 
-    /// Get path for a chunk file
-    fn chunk_path(&self, id: &str, chunk_index: usize) -> PathBuf {
-        self.recovery_dir
-            .join(format!("{id}.chunk.{}", chunk_index))
+    /// Get path for a chunk file of a generation
+    fn chunk_path(&self, id: &str, generation: u64, chunk_index: usize) -> PathBuf {
+        if generation == 0 {
+            self.recovery_dir.join(format!("{id}.chunk.{chunk_index}"))
+        } else {
+            self.recovery_dir
+                .join(format!("{id}.chunk.{generation}.{chunk_index}"))
+        }
     }
 
     /// List all chunk files for a given ID
@@ -186,7 +244,12 @@ impl RecoveryStorage {
     /// ## File Layout
     ///
     /// - `{id}.meta.json` - Contains RecoveryMetadata with embedded ChunkedRecoveryIndex
-    /// - `{id}.chunk.0`, `{id}.chunk.1`, ... - Raw binary content for each chunk
+    /// - `{id}.chunk.{generation}.0`, `{id}.chunk.{generation}.1`, ... - Raw
+    ///   binary content for each chunk
+    ///
+    /// The chunks are written under the next generation's names before the
+    /// metadata is switched to them, and the previous generation is deleted
+    /// last. A save interrupted part way leaves the previous save readable.
     pub fn save_recovery(
         &self,
         id: &str,
@@ -201,22 +264,27 @@ impl RecoveryStorage {
 
         let (meta_path, _content_path) = self.recovery_paths(id);
 
-        // Delete any existing chunk files first
-        self.delete_chunk_files(id)?;
-
         // Create chunked data structure
         let chunked_data = ChunkedRecoveryData::new(original_file_size, final_size, chunks);
 
+        // An unreadable previous index has no chunks worth keeping
+        let generation = self
+            .read_chunked_index(id)
+            .ok()
+            .flatten()
+            .map_or(0, |index| index.generation + 1);
+
         // Write each chunk to a separate file
         let mut total_chunk_bytes = 0u64;
         for (i, chunk) in chunked_data.chunks.iter().enumerate() {
-            let chunk_path = self.chunk_path(id, i);
+            let chunk_path = self.chunk_path(id, generation, i);
             self.atomic_write(&chunk_path, &chunk.content)?;
             total_chunk_bytes += chunk.content.len() as u64;
         }
 
         // Create the index (metadata without binary content)
-        let index = chunked_data.to_index();
+        let mut index = chunked_data.to_index();
+        index.generation = generation;
 
         // Get original file's mtime if it exists
         let original_mtime = original_path.and_then(|p| {
@@ -274,6 +342,17 @@ impl RecoveryStorage {
             .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
         self.atomic_write(&meta_path, meta_json.as_bytes())?;
 
+        // Only now that the metadata refers to the new chunks can the
+        // previous generation go
+        let current: Vec<_> = (0..chunked_data.chunks.len())
+            .map(|i| self.chunk_path(id, generation, i))
+            .collect();
+        for path in self.list_chunk_paths(id)? {
+            if !current.contains(&path) {
+                let _ = fs::remove_file(path);
+            }
+        }
+
         Ok(metadata)
     }
 
@@ -313,7 +392,7 @@ impl RecoveryStorage {
         // Load content for each chunk from its file
         let mut chunks = Vec::with_capacity(index.chunks.len());
         for (i, chunk_meta) in index.chunks.iter().enumerate() {
-            let chunk_path = self.chunk_path(id, i);
+            let chunk_path = self.chunk_path(id, index.generation, i);
             if !chunk_path.exists() {
                 return Err(io::Error::new(
                     io::ErrorKind::NotFound,
@@ -322,6 +401,18 @@ impl RecoveryStorage {
             }
 
             let content = fs::read(&chunk_path)?;
+            // A chunk that doesn't match the index was not written whole
+            if content.len() != chunk_meta.size {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!(
+                        "Chunk file {} has {} bytes, expected {}",
+                        chunk_path.display(),
+                        content.len(),
+                        chunk_meta.size
+                    ),
+                ));
+            }
 
             chunks.push(RecoveryChunk {
                 offset: chunk_meta.offset,
@@ -583,10 +674,10 @@ impl RecoveryStorage {
 
     /// Perform an atomic write: write to temp file, then rename
     ///
-    /// TODO: Migrate to async I/O to avoid blocking the main thread during recovery saves.
-    /// Currently we skip fsync for performance - this means editor crashes are safe (OS
-    /// page cache survives), but system crashes/power loss could leave corrupted recovery
-    /// files. Async I/O would let us have both safety and performance.
+    /// We skip fsync for performance - this means editor crashes are safe (OS
+    /// page cache survives), but system crashes/power loss could leave
+    /// truncated recovery files. Those are caught when reading chunks, as
+    /// their sizes no longer match the index.
     fn atomic_write(&self, target: &Path, content: &[u8]) -> io::Result<()> {
         let temp_path = target.with_extension("tmp");
 
@@ -733,6 +824,46 @@ mod tests {
         assert!(storage.load_entry("complete").unwrap().is_some());
     }
 
+    #[test]
+    fn test_migrate_legacy_layout() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path().to_path_buf();
+        let project = temp.path().join("project");
+        fs::create_dir_all(&project).unwrap();
+
+        // A crashed session in the flat layout
+        let legacy = RecoveryStorage::with_dir(root.clone());
+        legacy.ensure_dir().unwrap();
+        let info = SessionInfo {
+            working_dir: Some(project.clone()),
+            ..SessionInfo::new()
+        };
+        fs::write(
+            root.join(RecoveryStorage::SESSION_LOCK),
+            serde_json::to_string(&info).unwrap(),
+        )
+        .unwrap();
+        let chunks = vec![RecoveryChunk::new(0, 0, b"legacy".to_vec())];
+        legacy
+            .save_recovery("old", chunks, None, Some("Untitled-1"), None, 0, 6)
+            .unwrap();
+
+        let moved = RecoveryStorage::migrate_legacy_layout(&root).unwrap();
+        assert_eq!(moved, 3); // lock, metadata, chunk
+        assert!(legacy.list_entries().unwrap().is_empty());
+
+        let bucket =
+            RecoveryStorage::with_dir(RecoveryBucket::for_working_dir(&project).dir(&root));
+        assert_eq!(bucket.list_entries().unwrap().len(), 1);
+        assert_eq!(
+            bucket.read_session_lock().unwrap().unwrap().working_dir,
+            Some(project)
+        );
+
+        // Nothing left to migrate
+        assert_eq!(RecoveryStorage::migrate_legacy_layout(&root).unwrap(), 0);
+    }
+
     #[test]
     fn test_atomic_write_integrity() {
         let (storage, _temp) = create_test_storage();
@@ -788,15 +919,15 @@ mod tests {
         assert_eq!(metadata.original_file_size, original_size);
 
         // Verify chunk files exist
-        assert!(storage.chunk_path(id, 0).exists());
-        assert!(storage.chunk_path(id, 1).exists());
-        assert!(!storage.chunk_path(id, 2).exists()); // Only 2 chunks
+        assert!(storage.chunk_path(id, 0, 0).exists());
+        assert!(storage.chunk_path(id, 0, 1).exists());
+        assert!(!storage.chunk_path(id, 0, 2).exists()); // Only 2 chunks
 
         // Verify chunk file contents
-        let chunk0_content = fs::read(storage.chunk_path(id, 0)).unwrap();
+        let chunk0_content = fs::read(storage.chunk_path(id, 0, 0)).unwrap();
         assert_eq!(chunk0_content, b"INSERTED_AT_START");
 
-        let chunk1_content = fs::read(storage.chunk_path(id, 1)).unwrap();
+        let chunk1_content = fs::read(storage.chunk_path(id, 0, 1)).unwrap();
         assert_eq!(chunk1_content, b"REPLACED");
 
         // Verify metadata file contains chunked_index
@@ -902,18 +1033,18 @@ mod tests {
         // Verify files exist
         let (meta_path, _) = storage.recovery_paths(id);
         assert!(meta_path.exists());
-        assert!(storage.chunk_path(id, 0).exists());
-        assert!(storage.chunk_path(id, 1).exists());
-        assert!(storage.chunk_path(id, 2).exists());
+        assert!(storage.chunk_path(id, 0, 0).exists());
+        assert!(storage.chunk_path(id, 0, 1).exists());
+        assert!(storage.chunk_path(id, 0, 2).exists());
 
         // Delete recovery
         storage.delete_recovery(id).unwrap();
 
         // Verify all files are gone
         assert!(!meta_path.exists());
-        assert!(!storage.chunk_path(id, 0).exists());
-        assert!(!storage.chunk_path(id, 1).exists());
-        assert!(!storage.chunk_path(id, 2).exists());
+        assert!(!storage.chunk_path(id, 0, 0).exists());
+        assert!(!storage.chunk_path(id, 0, 1).exists());
+        assert!(!storage.chunk_path(id, 0, 2).exists());
     }
 
     #[test]
@@ -922,8 +1053,8 @@ mod tests {
         storage.ensure_dir().unwrap();
 
         // Create orphan chunk files (no metadata)
-        let orphan_chunk0 = storage.chunk_path("orphan", 0);
-        let orphan_chunk1 = storage.chunk_path("orphan", 1);
+        let orphan_chunk0 = storage.chunk_path("orphan", 0, 0);
+        let orphan_chunk1 = storage.chunk_path("orphan", 0, 1);
         fs::write(&orphan_chunk0, b"orphan chunk 0").unwrap();
         fs::write(&orphan_chunk1, b"orphan chunk 1").unwrap();
 
@@ -976,6 +1107,78 @@ mod tests {
         // Both should have valid checksums
     }
 
+    fn save_full(storage: &RecoveryStorage, id: &str, content: &[u8]) {
+        let chunks = vec![RecoveryChunk::new(0, 0, content.to_vec())];
+        storage
+            .save_recovery(id, chunks, None, None, None, 0, content.len())
+            .unwrap();
+    }
+
+    fn chunk_content(storage: &RecoveryStorage, id: &str) -> io::Result<Vec<u8>> {
+        let data = storage.read_chunked_content(id)?.unwrap();
+        Ok(data.chunks[0].content.clone())
+    }
+
+    #[test]
+    fn test_resave_switches_generation() {
+        let (storage, _temp) = create_test_storage();
+
+        save_full(&storage, "buf", b"first");
+        save_full(&storage, "buf", b"second");
+
+        assert_eq!(chunk_content(&storage, "buf").unwrap(), b"second");
+        // Only the new generation's chunk is left
+        assert_eq!(
+            storage.list_chunk_paths("buf").unwrap(),
+            vec![storage.chunk_path("buf", 1, 0)]
+        );
+    }
+
+    #[test]
+    fn test_interrupted_save_keeps_previous_content() {
+        let (storage, _temp) = create_test_storage();
+        save_full(&storage, "buf", b"saved content");
+
+        // The next save wrote part of its chunk, then the editor crashed
+        // before switching the metadata over
+        fs::write(storage.chunk_path("buf", 1, 0), b"new cont").unwrap();
+        fs::write(storage.recovery_dir.join("buf.chunk.1.tmp"), b"new").unwrap();
+
+        assert_eq!(chunk_content(&storage, "buf").unwrap(), b"saved content");
+
+        // The next complete save replaces both
+        save_full(&storage, "buf", b"new content");
+        assert_eq!(chunk_content(&storage, "buf").unwrap(), b"new content");
+        assert_eq!(storage.list_chunk_paths("buf").unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_crash_between_buffer_writes_recovers_the_others() {
+        let (storage, _temp) = create_test_storage();
+        save_full(&storage, "a", b"buffer a");
+        save_full(&storage, "b", b"buffer b");
+        save_full(&storage, "c", b"buffer c");
+
+        // A crash cut one buffer's chunk short and another's metadata
+        fs::write(storage.chunk_path("b", 0, 0), b"buf").unwrap();
+        let (meta_path, _) = storage.recovery_paths("c");
+        let meta = fs::read(&meta_path).unwrap();
+        fs::write(&meta_path, &meta[..meta.len() / 2]).unwrap();
+
+        let mut ids: Vec<_> = storage
+            .list_entries()
+            .unwrap()
+            .into_iter()
+            .map(|entry| entry.id)
+            .collect();
+        ids.sort();
+        assert_eq!(ids, vec!["a", "b"]);
+
+        assert_eq!(chunk_content(&storage, "a").unwrap(), b"buffer a");
+        let err = chunk_content(&storage, "b").unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+
     // ========================================================================
     // Property tests for recovery persistence/restore layer
     // ========================================================================
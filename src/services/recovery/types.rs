@@ -72,8 +72,15 @@ pub struct ChunkedRecoveryIndex {
     pub original_size: usize,
     /// Total size after applying all chunks
     pub final_size: usize,
-    /// Metadata for each chunk (content stored in separate .chunk.N files)
+    /// Metadata for each chunk (content stored in separate chunk files)
     pub chunks: Vec<ChunkMeta>,
+    /// Which save of the buffer the chunk files belong to
+    ///
+    /// Each save writes its chunks under new names before the index is
+    /// switched to them, so the index never refers to a partly written set.
+    /// Generation 0 uses the `{id}.chunk.N` names of format version 2.
+    #[serde(default)]
+    pub generation: u64,
 }
 
 /// Chunked recovery data with full content (used in memory)
@@ -103,6 +110,7 @@ impl ChunkedRecoveryData {
             original_size: self.original_size,
             final_size: self.final_size,
             chunks: self.chunks.iter().map(|c| c.to_meta()).collect(),
+            generation: 0,
         }
     }
 }
@@ -148,7 +156,10 @@ pub struct RecoveryMetadata {
 
 impl RecoveryMetadata {
     /// Current format version
-    pub const FORMAT_VERSION: u32 = 2;
+    ///
+    /// Version 3 added chunk generations, see
+    /// [`ChunkedRecoveryIndex::generation`].
+    pub const FORMAT_VERSION: u32 = 3;
 
     /// Create new metadata
     pub fn new(
@@ -188,6 +199,7 @@ impl RecoveryMetadata {
         self.content_size = content_size;
         self.line_count = line_count;
         self.chunk_count = chunk_count;
+        self.format_version = Self::FORMAT_VERSION;
     }
 
     /// Get a display name for this recovery entry
@@ -342,7 +354,7 @@ pub enum RecoveryResult {
 
 /// Check if a process with the given PID is running
 #[cfg(unix)]
-fn is_process_running(pid: u32) -> bool {
+pub(crate) fn is_process_running(pid: u32) -> bool {
     // On Unix, we can use kill with signal 0 to check if process exists
     // Returns 0 if process exists and we can signal it
     // Returns -1 with EPERM if process exists but we can't signal it
@@ -357,7 +369,7 @@ fn is_process_running(pid: u32) -> bool {
 }
 
 #[cfg(windows)]
-fn is_process_running(pid: u32) -> bool {
+pub(crate) fn is_process_running(pid: u32) -> bool {
     use windows_sys::Win32::Foundation::{CloseHandle, STILL_ACTIVE};
     use windows_sys::Win32::System::Threading::{
         GetExitCodeProcess, OpenProcess, PROCESS_QUERY_LIMITED_INFORMATION,
@@ -376,11 +388,42 @@ fn is_process_running(pid: u32) -> bool {
 }
 
 #[cfg(not(any(unix, windows)))]
-fn is_process_running(_pid: u32) -> bool {
+pub(crate) fn is_process_running(_pid: u32) -> bool {
     // On other platforms, assume not running (safer for recovery)
     false
 }
 
+/// The set of recovery files a session reads and writes
+///
+/// Sessions with a working directory keep their recovery files in a bucket of
+/// their own, so a crash in one project never resurrects buffers into another.
+/// Untitled buffers from sessions without a project go to the global bucket.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RecoveryBucket {
+    /// Recovery files for a project, keyed by its canonical working directory
+    Project(PathBuf),
+    /// Recovery files for sessions without a project
+    Global,
+}
+
+impl RecoveryBucket {
+    /// Bucket for a working directory (canonicalized when possible)
+    pub fn for_working_dir(working_dir: &std::path::Path) -> Self {
+        let dir = working_dir
+            .canonicalize()
+            .unwrap_or_else(|_| working_dir.to_path_buf());
+        Self::Project(dir)
+    }
+
+    /// Directory holding this bucket's files under the recovery directory
+    pub fn dir(&self, recovery_root: &std::path::Path) -> PathBuf {
+        match self {
+            Self::Project(dir) => recovery_root.join("projects").join(path_hash(dir)),
+            Self::Global => recovery_root.join("global"),
+        }
+    }
+}
+
 /// Generate a stable hash for a file path (used as recovery file ID)
 pub fn path_hash(path: &std::path::Path) -> String {
     use sha2::{Digest, Sha256};
@@ -413,6 +456,18 @@ mod tests {
         assert_eq!(hash, path_hash(path));
     }
 
+    #[test]
+    fn test_recovery_bucket_dirs() {
+        let root = std::path::Path::new("/data/recovery");
+        let a = RecoveryBucket::Project(PathBuf::from("/work/a"));
+        let b = RecoveryBucket::Project(PathBuf::from("/work/b"));
+
+        assert_eq!(a.dir(root), a.dir(root));
+        assert_ne!(a.dir(root), b.dir(root));
+        assert!(a.dir(root).starts_with(root.join("projects")));
+        assert_eq!(RecoveryBucket::Global.dir(root), root.join("global"));
+    }
+
     #[test]
     fn test_recovery_metadata_new() {
         let meta = RecoveryMetadata::new(
@@ -4,13 +4,19 @@
 //! - Check for new releases by fetching a GitHub releases API endpoint
 //! - Detect the installation method (Homebrew, npm, cargo, etc.) based on executable path
 //! - Provide appropriate update commands based on installation method
-//! - Periodic update checking with automatic re-spawn every hour
+//! - Periodic update checking every hour on the editor's worker pool
+//! - Self-update of installs from the release archives: a resumable download
+//!   of the archive, a sha256 check against its published checksum, and
+//!   replacing the running binary (see [`SelfUpdate`])
 
+use crate::services::time_source::SharedTimeSource;
+use crate::services::worker_pool::{JobHandle, JobPriority, WorkerPool};
 use std::env;
-use std::path::Path;
-use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::mpsc::{self, Receiver, TryRecvError};
-use std::sync::Arc;
+use std::fmt;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
 use std::thread::{self, JoinHandle};
 use std::time::{Duration, Instant};
 
@@ -101,16 +107,27 @@ pub const DEFAULT_UPDATE_CHECK_INTERVAL: Duration = Duration::from_secs(60 * 60)
 
 /// Handle to a periodic update checker that runs in the background.
 ///
-/// The checker runs every hour and provides results via `poll_result()`.
+/// Each check runs as a background job on the editor's worker pool. The first
+/// check is submitted right away; later ones are submitted by `poll_result()`
+/// once the interval has elapsed, so polling drives the schedule.
 /// When a check finds an update, the result is stored until retrieved.
 pub struct PeriodicUpdateChecker {
+    /// Worker pool running the checks
+    pool: WorkerPool,
+    /// Releases API URL to check
+    url: String,
+    /// Time between checks
+    check_interval: Duration,
+    /// Sender handed to each check job
+    sender: Sender<Result<ReleaseCheckResult, String>>,
     /// Receiver for update check results
     receiver: Receiver<Result<ReleaseCheckResult, String>>,
-    /// Signal to stop the background thread
-    stop_signal: Arc<AtomicBool>,
-    /// Background thread handle
-    #[allow(dead_code)]
-    thread: JoinHandle<()>,
+    /// The check currently queued or running
+    pending: Option<JobHandle>,
+    /// Clock the interval is measured on
+    time_source: SharedTimeSource,
+    /// When the last check was submitted
+    last_submit_time: Instant,
     /// Last successful result (cached)
     last_result: Option<ReleaseCheckResult>,
     /// Time of last check (for tracking)
@@ -118,14 +135,43 @@ pub struct PeriodicUpdateChecker {
 }
 
 impl PeriodicUpdateChecker {
+    /// Submit a check to the worker pool
+    fn submit_check(&mut self) {
+        tracing::debug!("Periodic update check starting");
+        let url = self.url.clone();
+        let sender = self.sender.clone();
+        self.pending = Some(self.pool.submit(
+            "release-check",
+            JobPriority::Background,
+            move |token| {
+                let result = check_for_update(&url);
+                if !token.is_cancelled() {
+                    let _ = sender.send(result);
+                }
+                None
+            },
+        ));
+        self.last_submit_time = self.time_source.now();
+    }
+
     /// Poll for a new update check result without blocking.
     ///
     /// Returns `Some(result)` if a new check completed, `None` if no new result.
     /// Successful results are cached and can be retrieved via `get_cached_result()`.
+    /// Also submits the next check once the interval has elapsed.
     pub fn poll_result(&mut self) -> Option<Result<ReleaseCheckResult, String>> {
+        if self.pending.as_ref().is_some_and(JobHandle::is_finished) {
+            self.pending = None;
+        }
+        if self.pending.is_none()
+            && self.time_source.elapsed_since(self.last_submit_time) >= self.check_interval
+        {
+            self.submit_check();
+        }
+
         match self.receiver.try_recv() {
             Ok(result) => {
-                self.last_check_time = Some(Instant::now());
+                self.last_check_time = Some(self.time_source.now());
                 if let Ok(ref release_result) = result {
                     tracing::debug!(
                         "Periodic update check completed: update_available={}",
@@ -135,11 +181,7 @@ impl PeriodicUpdateChecker {
                 }
                 Some(result)
             }
-            Err(TryRecvError::Empty) => None,
-            Err(TryRecvError::Disconnected) => {
-                tracing::debug!("Periodic update checker thread disconnected");
-                None
-            }
+            Err(TryRecvError::Empty) | Err(TryRecvError::Disconnected) => None,
         }
     }
 
@@ -170,8 +212,10 @@ impl PeriodicUpdateChecker {
 
 impl Drop for PeriodicUpdateChecker {
     fn drop(&mut self) {
-        // Signal the background thread to stop
-        self.stop_signal.store(true, Ordering::SeqCst);
+        // Drop a check that hasn't started yet and discard one in flight
+        if let Some(pending) = &self.pending {
+            pending.cancel();
+        }
     }
 }
 
@@ -179,8 +223,17 @@ impl Drop for PeriodicUpdateChecker {
 ///
 /// The checker immediately runs the first check, then repeats every hour.
 /// Results are available via `poll_result()` on the returned handle.
-pub fn start_periodic_update_check(releases_url: &str) -> PeriodicUpdateChecker {
-    start_periodic_update_check_with_interval(releases_url, DEFAULT_UPDATE_CHECK_INTERVAL)
+pub fn start_periodic_update_check(
+    pool: &WorkerPool,
+    releases_url: &str,
+    time_source: SharedTimeSource,
+) -> PeriodicUpdateChecker {
+    start_periodic_update_check_with_interval(
+        pool,
+        releases_url,
+        DEFAULT_UPDATE_CHECK_INTERVAL,
+        time_source,
+    )
 }
 
 /// Start a periodic update checker with a custom check interval.
@@ -189,68 +242,36 @@ pub fn start_periodic_update_check(releases_url: &str) -> PeriodicUpdateChecker
 /// the periodic behavior without waiting for an hour.
 ///
 /// # Arguments
+/// * `pool` - Worker pool to run the checks on
 /// * `releases_url` - The GitHub releases API URL to check
 /// * `check_interval` - Duration between checks
+/// * `time_source` - Clock the interval is measured on
 pub fn start_periodic_update_check_with_interval(
+    pool: &WorkerPool,
     releases_url: &str,
     check_interval: Duration,
+    time_source: SharedTimeSource,
 ) -> PeriodicUpdateChecker {
     tracing::debug!(
         "Starting periodic update checker with interval {:?}",
         check_interval
     );
-    let url = releases_url.to_string();
-    let (tx, rx) = mpsc::channel();
-    let stop_signal = Arc::new(AtomicBool::new(false));
-    let stop_signal_clone = stop_signal.clone();
-
-    // Use a smaller sleep increment for shorter intervals
-    let sleep_increment = if check_interval < Duration::from_secs(10) {
-        Duration::from_millis(10)
-    } else {
-        Duration::from_secs(1)
-    };
-
-    let handle = thread::spawn(move || {
-        // Run initial check immediately
-        let result = check_for_update(&url);
-        if tx.send(result).is_err() {
-            return; // Receiver dropped, exit
-        }
-
-        // Then check periodically
-        loop {
-            // Sleep in small increments to allow quick shutdown
-            let sleep_end = Instant::now() + check_interval;
-            while Instant::now() < sleep_end {
-                if stop_signal_clone.load(Ordering::SeqCst) {
-                    tracing::debug!("Periodic update checker stopping");
-                    return;
-                }
-                thread::sleep(sleep_increment);
-            }
-
-            // Check if we should stop before making a new request
-            if stop_signal_clone.load(Ordering::SeqCst) {
-                tracing::debug!("Periodic update checker stopping");
-                return;
-            }
-
-            tracing::debug!("Periodic update check starting");
-            let result = check_for_update(&url);
-            if tx.send(result).is_err() {
-                return; // Receiver dropped, exit
-            }
-        }
-    });
-
-    PeriodicUpdateChecker {
-        receiver: rx,
-        stop_signal,
-        thread: handle,
+    let (sender, receiver) = mpsc::channel();
+    let mut checker = PeriodicUpdateChecker {
+        pool: pool.clone(),
+        url: releases_url.to_string(),
+        check_interval,
+        sender,
+        receiver,
+        pending: None,
+        last_submit_time: time_source.now(),
+        time_source,
         last_result: None,
         last_check_time: None,
-    }
+    };
+    // Run initial check immediately
+    checker.submit_check();
+    checker
 }
 
 /// Start a background update check
@@ -424,6 +445,520 @@ pub fn check_for_update(releases_url: &str) -> Result<ReleaseCheckResult, String
     })
 }
 
+/// Base URL the release archives are downloaded from
+pub const DEFAULT_DOWNLOAD_URL: &str = "https://github.com/sinelaw/fresh/releases/download";
+
+/// Attempts at a download before giving up; each one resumes the last
+const DOWNLOAD_ATTEMPTS: u32 = 5;
+
+/// Wait before the second attempt, doubled for each one after
+const DOWNLOAD_RETRY_DELAY: Duration = Duration::from_millis(500);
+
+/// Longest wait for a connection or for more data from one
+const DOWNLOAD_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Suffix of a download that hasn't finished
+const PARTIAL_SUFFIX: &str = "part";
+
+/// Suffix of a new binary waiting to replace the installed one
+const STAGED_SUFFIX: &str = "update";
+
+/// Suffix of the replaced binary, where it can't be removed while running
+const OLD_SUFFIX: &str = "old";
+
+/// Target triple of the running build as the release archives name it, None
+/// for platforms without a release archive
+pub fn current_target() -> Option<&'static str> {
+    if cfg!(target_env = "musl") {
+        return None;
+    }
+    match (env::consts::OS, env::consts::ARCH) {
+        ("linux", "x86_64") => Some("x86_64-unknown-linux-gnu"),
+        ("linux", "aarch64") => Some("aarch64-unknown-linux-gnu"),
+        ("macos", "x86_64") => Some("x86_64-apple-darwin"),
+        ("macos", "aarch64") => Some("aarch64-apple-darwin"),
+        ("windows", "x86_64") => Some("x86_64-pc-windows-msvc"),
+        _ => None,
+    }
+}
+
+/// File name of the release archive for a target triple
+pub fn release_asset_name(target: &str) -> Option<String> {
+    match target {
+        "x86_64-unknown-linux-gnu"
+        | "aarch64-unknown-linux-gnu"
+        | "x86_64-apple-darwin"
+        | "aarch64-apple-darwin" => Some(format!("fresh-editor-{}.tar.xz", target)),
+        "x86_64-pc-windows-msvc" => Some(format!("fresh-editor-{}.zip", target)),
+        _ => None,
+    }
+}
+
+/// Why a self-update didn't happen
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SelfUpdateError {
+    /// Installed by a package manager, which should do the update
+    ManagedInstall(InstallMethod),
+    /// No release archive is built for this platform
+    UnsupportedPlatform,
+    /// The directory of the binary can't be written
+    NotWritable(PathBuf),
+    /// The archive or its checksum couldn't be downloaded
+    Download(String),
+    /// No checksum is published for the archive
+    ChecksumMissing,
+    /// The archive doesn't match its published checksum
+    ChecksumMismatch { expected: String, actual: String },
+    /// The archive didn't unpack, or holds no binary
+    Extract(String),
+    /// Reading or writing a file failed
+    Io(String),
+}
+
+impl fmt::Display for SelfUpdateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ManagedInstall(method) => write!(f, "installed via {:?}", method),
+            Self::UnsupportedPlatform => write!(f, "no release archive for this platform"),
+            Self::NotWritable(dir) => write!(f, "cannot write to {}", dir.display()),
+            Self::Download(e) => write!(f, "download failed: {}", e),
+            Self::ChecksumMissing => write!(f, "no checksum published"),
+            Self::ChecksumMismatch { expected, actual } => {
+                write!(
+                    f,
+                    "checksum mismatch: expected {}, got {}",
+                    expected, actual
+                )
+            }
+            Self::Extract(e) => write!(f, "cannot unpack the release: {}", e),
+            Self::Io(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl From<io::Error> for SelfUpdateError {
+    fn from(e: io::Error) -> Self {
+        Self::Io(e.to_string())
+    }
+}
+
+/// Where a self-update is, reported while it runs
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SelfUpdateProgress {
+    /// Bytes of the archive downloaded so far, out of `total` if known
+    Downloading {
+        received: u64,
+        total: Option<u64>,
+    },
+    Verifying,
+    Installing,
+    Finished(Result<SelfUpdateOutcome, SelfUpdateError>),
+}
+
+/// How a successful self-update left the binary
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SelfUpdateOutcome {
+    /// The binary at this path is the new version; restarting runs it
+    Installed(PathBuf),
+    /// The binary at this path was locked; the new one is staged next to it
+    /// and replaces it when the editor next starts
+    Staged(PathBuf),
+}
+
+/// Update of an installed binary to a release
+///
+/// [`SelfUpdate::run`] downloads the release archive into `cache_dir`,
+/// resuming an earlier partial download, checks it against the published
+/// `<archive>.sha256`, unpacks it and moves the new binary over `exe_path`.
+/// It blocks, so it runs on a worker thread.
+#[derive(Debug, Clone)]
+pub struct SelfUpdate {
+    /// Version to install, without a `v` prefix
+    pub version: String,
+    /// Base URL of the release downloads
+    pub download_url: String,
+    /// File name of the release archive
+    pub asset: String,
+    /// Where the archive is downloaded and unpacked
+    pub cache_dir: PathBuf,
+    /// Binary to replace
+    pub exe_path: PathBuf,
+}
+
+impl SelfUpdate {
+    /// Update of the running binary to `version`
+    ///
+    /// Refuses installs that a package manager owns and platforms without a
+    /// release archive.
+    pub fn for_current_exe(
+        version: &str,
+        download_url: &str,
+        cache_dir: &Path,
+    ) -> Result<Self, SelfUpdateError> {
+        let exe_path = env::current_exe()?;
+        let exe_path = exe_path.canonicalize().unwrap_or(exe_path);
+        match detect_install_method_from_path(&exe_path) {
+            InstallMethod::Unknown => {}
+            method => return Err(SelfUpdateError::ManagedInstall(method)),
+        }
+        let asset = current_target()
+            .and_then(release_asset_name)
+            .ok_or(SelfUpdateError::UnsupportedPlatform)?;
+        Ok(Self {
+            version: version.to_string(),
+            download_url: download_url.to_string(),
+            asset,
+            cache_dir: cache_dir.to_path_buf(),
+            exe_path,
+        })
+    }
+
+    /// URL of the release archive
+    pub fn asset_url(&self) -> String {
+        format!(
+            "{}/v{}/{}",
+            self.download_url.trim_end_matches('/'),
+            self.version,
+            self.asset
+        )
+    }
+
+    /// Download, verify and install the release, reporting progress
+    pub fn run(
+        &self,
+        progress: &mut dyn FnMut(SelfUpdateProgress),
+    ) -> Result<SelfUpdateOutcome, SelfUpdateError> {
+        let install_dir = self
+            .exe_path
+            .parent()
+            .ok_or_else(|| SelfUpdateError::NotWritable(self.exe_path.clone()))?;
+        // Before downloading anything, so a read-only install fails fast
+        check_writable(install_dir)?;
+
+        fs::create_dir_all(&self.cache_dir)?;
+        let archive = self.cache_dir.join(&self.asset);
+        let url = self.asset_url();
+        if !archive.exists() {
+            download_resumable(&url, &archive, &mut |received, total| {
+                progress(SelfUpdateProgress::Downloading { received, total })
+            })?;
+        }
+
+        progress(SelfUpdateProgress::Verifying);
+        let expected = fetch_checksum(&format!("{}.sha256", url))?;
+        if let Err(e) = verify_checksum(&archive, &expected) {
+            // Download it again next time rather than resuming a bad file
+            let _ = fs::remove_file(&archive);
+            return Err(e);
+        }
+
+        progress(SelfUpdateProgress::Installing);
+        let unpack_dir = self.cache_dir.join(format!("fresh-{}", self.version));
+        let _ = fs::remove_dir_all(&unpack_dir);
+        let binary = extract_binary(&archive, &unpack_dir)?;
+        let outcome = install_binary(&binary, &self.exe_path);
+        let _ = fs::remove_dir_all(&unpack_dir);
+        if outcome.is_ok() {
+            let _ = fs::remove_file(&archive);
+        }
+        outcome
+    }
+}
+
+/// `path` with `.suffix` added to its file name
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".");
+    name.push(suffix);
+    path.with_file_name(name)
+}
+
+/// Fail with [`SelfUpdateError::NotWritable`] unless files can be created in `dir`
+fn check_writable(dir: &Path) -> Result<(), SelfUpdateError> {
+    let probe = dir.join(format!(".fresh-update-{}", std::process::id()));
+    match File::create(&probe) {
+        Ok(_) => {
+            let _ = fs::remove_file(&probe);
+            Ok(())
+        }
+        Err(e) if e.kind() == io::ErrorKind::PermissionDenied => {
+            Err(SelfUpdateError::NotWritable(dir.to_path_buf()))
+        }
+        Err(e) => Err(e.into()),
+    }
+}
+
+fn download_agent() -> ureq::Agent {
+    ureq::AgentBuilder::new()
+        .timeout_connect(DOWNLOAD_TIMEOUT)
+        .timeout_read(DOWNLOAD_TIMEOUT)
+        .user_agent("fresh-editor-self-update")
+        .build()
+}
+
+/// Download `url` to `dest`
+///
+/// Bytes go to `dest.part` first, and a download that breaks off is
+/// continued from where it stopped with a ranged request, both within this
+/// call (up to [`DOWNLOAD_ATTEMPTS`] times) and by a later call that finds
+/// the partial file. A server that ignores the range sends the whole file
+/// again, which replaces the partial one.
+pub fn download_resumable(
+    url: &str,
+    dest: &Path,
+    progress: &mut dyn FnMut(u64, Option<u64>),
+) -> Result<(), SelfUpdateError> {
+    let partial = with_suffix(dest, PARTIAL_SUFFIX);
+    let agent = download_agent();
+    let mut attempt = 0;
+    loop {
+        match download_range(&agent, url, &partial, progress) {
+            Ok(()) => break,
+            Err(e) => {
+                attempt += 1;
+                if attempt >= DOWNLOAD_ATTEMPTS {
+                    return Err(SelfUpdateError::Download(e));
+                }
+                tracing::debug!("Download of {} interrupted, resuming: {}", url, e);
+                thread::sleep(DOWNLOAD_RETRY_DELAY * 2u32.pow(attempt - 1));
+            }
+        }
+    }
+    fs::rename(&partial, dest)?;
+    Ok(())
+}
+
+/// Continue the download in `partial` from its current length
+fn download_range(
+    agent: &ureq::Agent,
+    url: &str,
+    partial: &Path,
+    progress: &mut dyn FnMut(u64, Option<u64>),
+) -> Result<(), String> {
+    let offset = fs::metadata(partial).map(|m| m.len()).unwrap_or(0);
+    let mut request = agent.get(url);
+    if offset > 0 {
+        request = request.set("Range", &format!("bytes={}-", offset));
+    }
+    let response = match request.call() {
+        Ok(response) => response,
+        Err(ureq::Error::Status(416, _)) => {
+            // The partial file isn't a prefix of this file; start over
+            let _ = fs::remove_file(partial);
+            return Err("requested range not satisfiable".to_string());
+        }
+        Err(e) => return Err(e.to_string()),
+    };
+
+    let resumed = offset > 0 && response.status() == 206;
+    let (mut received, total) = if resumed {
+        let total = response
+            .header("Content-Range")
+            .and_then(|range| range.rsplit('/').next())
+            .and_then(|total| total.trim().parse().ok());
+        (offset, total)
+    } else {
+        let total = response
+            .header("Content-Length")
+            .and_then(|len| len.trim().parse().ok());
+        (0, total)
+    };
+    let mut file = OpenOptions::new()
+        .create(true)
+        .write(true)
+        .append(resumed)
+        .truncate(!resumed)
+        .open(partial)
+        .map_err(|e| e.to_string())?;
+
+    progress(received, total);
+    let mut reader = response.into_reader();
+    let mut buf = vec![0u8; 64 * 1024];
+    loop {
+        let n = reader.read(&mut buf).map_err(|e| e.to_string())?;
+        if n == 0 {
+            break;
+        }
+        file.write_all(&buf[..n]).map_err(|e| e.to_string())?;
+        received += n as u64;
+        progress(received, total);
+    }
+    file.flush().map_err(|e| e.to_string())?;
+
+    match total {
+        Some(total) if received < total => Err(format!(
+            "connection closed after {} of {} bytes",
+            received, total
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Download a `sha256sum`-style checksum file and return its hash
+fn fetch_checksum(url: &str) -> Result<String, SelfUpdateError> {
+    let body = match download_agent().get(url).call() {
+        Ok(response) => response
+            .into_string()
+            .map_err(|e| SelfUpdateError::Download(e.to_string()))?,
+        Err(ureq::Error::Status(404, _)) => return Err(SelfUpdateError::ChecksumMissing),
+        Err(e) => return Err(SelfUpdateError::Download(e.to_string())),
+    };
+    parse_checksum(&body).ok_or(SelfUpdateError::ChecksumMissing)
+}
+
+/// Hash in `sha256sum` output (`<hash>  <file name>`), lowercased
+fn parse_checksum(text: &str) -> Option<String> {
+    let hash = text.split_whitespace().next()?;
+    (hash.len() == 64 && hash.chars().all(|c| c.is_ascii_hexdigit()))
+        .then(|| hash.to_ascii_lowercase())
+}
+
+/// Fail with [`SelfUpdateError::ChecksumMismatch`] unless the sha256 of
+/// `path` is `expected`
+pub fn verify_checksum(path: &Path, expected: &str) -> Result<(), SelfUpdateError> {
+    use sha2::{Digest, Sha256};
+    let mut hasher = Sha256::new();
+    let mut file = File::open(path)?;
+    io::copy(&mut file, &mut hasher)?;
+    let actual: String = hasher
+        .finalize()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect();
+    if actual.eq_ignore_ascii_case(expected) {
+        Ok(())
+    } else {
+        Err(SelfUpdateError::ChecksumMismatch {
+            expected: expected.to_string(),
+            actual,
+        })
+    }
+}
+
+/// Unpack a release archive into `dir` and return the path of its binary
+///
+/// Uses the system `tar`, which reads `.tar.xz` everywhere and `.zip` on
+/// Windows.
+fn extract_binary(archive: &Path, dir: &Path) -> Result<PathBuf, SelfUpdateError> {
+    fs::create_dir_all(dir)?;
+    let output = std::process::Command::new("tar")
+        .arg("-xf")
+        .arg(archive)
+        .arg("-C")
+        .arg(dir)
+        .output()
+        .map_err(|e| SelfUpdateError::Extract(format!("cannot run tar: {}", e)))?;
+    if !output.status.success() {
+        return Err(SelfUpdateError::Extract(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ));
+    }
+    find_binary(dir)
+        .ok_or_else(|| SelfUpdateError::Extract(format!("no {} in the archive", binary_name())))
+}
+
+fn binary_name() -> &'static str {
+    if cfg!(windows) {
+        "fresh.exe"
+    } else {
+        "fresh"
+    }
+}
+
+/// The binary in an unpacked archive: at its top or in its one directory
+fn find_binary(dir: &Path) -> Option<PathBuf> {
+    let direct = dir.join(binary_name());
+    if direct.is_file() {
+        return Some(direct);
+    }
+    fs::read_dir(dir)
+        .ok()?
+        .flatten()
+        .map(|entry| entry.path().join(binary_name()))
+        .find(|path| path.is_file())
+}
+
+/// Whether a rename failed because another process has the file open
+fn is_locked(e: &io::Error) -> bool {
+    // ERROR_ACCESS_DENIED and ERROR_SHARING_VIOLATION
+    cfg!(windows) && matches!(e.raw_os_error(), Some(5 | 32))
+}
+
+/// Move `staged` over `exe`
+///
+/// On Unix the rename replaces the binary atomically, even while it runs.
+/// Windows can't replace a running binary but can rename it, so the old one
+/// is moved aside first and removed on the next start.
+fn swap_binary(staged: &Path, exe: &Path) -> io::Result<()> {
+    if cfg!(unix) {
+        return fs::rename(staged, exe);
+    }
+    let old = with_suffix(exe, OLD_SUFFIX);
+    let _ = fs::remove_file(&old);
+    fs::rename(exe, &old)?;
+    if let Err(e) = fs::rename(staged, exe) {
+        let _ = fs::rename(&old, exe);
+        return Err(e);
+    }
+    let _ = fs::remove_file(&old);
+    Ok(())
+}
+
+/// Replace the binary at `exe` with `new_binary`
+///
+/// The new binary is copied next to `exe` first, so the final rename stays
+/// on one file system. If `exe` is locked the copy stays there and
+/// [`finish_pending_update`] puts it in place on the next start.
+pub fn install_binary(new_binary: &Path, exe: &Path) -> Result<SelfUpdateOutcome, SelfUpdateError> {
+    let not_writable = |e: io::Error| match e.kind() {
+        io::ErrorKind::PermissionDenied => {
+            SelfUpdateError::NotWritable(exe.parent().unwrap_or(exe).to_path_buf())
+        }
+        _ => e.into(),
+    };
+    let staged = with_suffix(exe, STAGED_SUFFIX);
+    fs::copy(new_binary, &staged).map_err(not_writable)?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = fs::metadata(exe)
+            .map(|m| m.permissions().mode())
+            .unwrap_or(0o755);
+        fs::set_permissions(&staged, fs::Permissions::from_mode(mode | 0o111))?;
+    }
+    match swap_binary(&staged, exe) {
+        Ok(()) => Ok(SelfUpdateOutcome::Installed(exe.to_path_buf())),
+        Err(e) if is_locked(&e) => Ok(SelfUpdateOutcome::Staged(exe.to_path_buf())),
+        Err(e) => {
+            let _ = fs::remove_file(&staged);
+            Err(not_writable(e))
+        }
+    }
+}
+
+/// Finish an update that was staged because the binary was locked, and
+/// remove a binary an update moved aside
+///
+/// Called at startup. Returns true if a staged binary was put in place; it
+/// runs from the next start.
+pub fn finish_pending_update(exe: &Path) -> bool {
+    let _ = fs::remove_file(with_suffix(exe, OLD_SUFFIX));
+    let staged = with_suffix(exe, STAGED_SUFFIX);
+    if !staged.is_file() {
+        return false;
+    }
+    match swap_binary(&staged, exe) {
+        Ok(()) => {
+            tracing::info!("Installed staged update of {}", exe.display());
+            true
+        }
+        Err(e) => {
+            tracing::warn!("Staged update of {} still pending: {}", exe.display(), e);
+            false
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -579,6 +1114,7 @@ mod tests {
         assert!(is_newer_version(CURRENT_VERSION, &version));
     }
 
+    use crate::services::time_source::{RealTimeSource, TestTimeSource};
     use std::sync::mpsc as std_mpsc;
 
     /// Test helper: start a local HTTP server that returns a mock release JSON
@@ -630,9 +1166,14 @@ mod tests {
     fn test_periodic_update_checker_with_local_server() {
         // Test that the production periodic checker works with a real HTTP server
         let (stop_tx, url) = start_mock_release_server("99.0.0");
+        let pool = WorkerPool::new(1, None);
 
-        let mut checker =
-            start_periodic_update_check_with_interval(&url, Duration::from_millis(50));
+        let mut checker = start_periodic_update_check_with_interval(
+            &pool,
+            &url,
+            Duration::from_millis(50),
+            RealTimeSource::shared(),
+        );
 
         // Wait for initial result
         let start = Instant::now();
@@ -659,8 +1200,14 @@ mod tests {
     fn test_periodic_update_checker_shutdown_clean() {
         // Test that the checker shuts down cleanly without hanging
         let (stop_tx, url) = start_mock_release_server("99.0.0");
+        let pool = WorkerPool::new(1, None);
 
-        let checker = start_periodic_update_check_with_interval(&url, Duration::from_millis(50));
+        let checker = start_periodic_update_check_with_interval(
+            &pool,
+            &url,
+            Duration::from_millis(50),
+            RealTimeSource::shared(),
+        );
 
         // Let it run briefly
         thread::sleep(Duration::from_millis(100));
@@ -684,9 +1231,14 @@ mod tests {
     fn test_periodic_update_checker_multiple_cycles_production() {
         // Test that the production checker produces multiple results over time
         let (stop_tx, url) = start_mock_release_server("99.0.0");
+        let pool = WorkerPool::new(1, None);
 
-        let mut checker =
-            start_periodic_update_check_with_interval(&url, Duration::from_millis(30));
+        let mut checker = start_periodic_update_check_with_interval(
+            &pool,
+            &url,
+            Duration::from_millis(30),
+            RealTimeSource::shared(),
+        );
 
         let mut result_count = 0;
         let start = Instant::now();
@@ -714,9 +1266,14 @@ mod tests {
     fn test_periodic_update_checker_no_update_when_current() {
         // Test behavior when server returns current version (no update)
         let (stop_tx, url) = start_mock_release_server(CURRENT_VERSION);
+        let pool = WorkerPool::new(1, None);
 
-        let mut checker =
-            start_periodic_update_check_with_interval(&url, Duration::from_secs(3600));
+        let mut checker = start_periodic_update_check_with_interval(
+            &pool,
+            &url,
+            Duration::from_secs(3600),
+            RealTimeSource::shared(),
+        );
 
         // Wait for initial result
         let start = Instant::now();
@@ -736,13 +1293,58 @@ mod tests {
         let _ = stop_tx.send(());
     }
 
+    #[test]
+    fn test_periodic_update_checker_interval_on_virtual_clock() {
+        let (stop_tx, url) = start_mock_release_server("99.0.0");
+        let pool = WorkerPool::new(1, None);
+        let time = TestTimeSource::shared();
+        let interval = Duration::from_secs(3600);
+
+        let mut checker =
+            start_periodic_update_check_with_interval(&pool, &url, interval, time.clone());
+
+        // The first check answers over real I/O
+        let start = Instant::now();
+        while start.elapsed() < Duration::from_secs(2) {
+            if checker.poll_result().is_some() {
+                break;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        assert!(checker.get_cached_result().is_some());
+        while checker
+            .pending
+            .as_ref()
+            .is_some_and(|job| !job.is_finished())
+        {
+            thread::sleep(Duration::from_millis(10));
+        }
+
+        time.advance(interval - Duration::from_secs(1));
+        checker.poll_result();
+        assert!(checker.pending.is_none(), "checked before the interval");
+
+        time.advance(Duration::from_secs(1));
+        checker.poll_result();
+        assert!(checker.pending.is_some(), "didn't check after the interval");
+
+        drop(checker);
+        let _ = stop_tx.send(());
+    }
+
     #[test]
     fn test_periodic_update_checker_api_before_result() {
         // Test that API methods work correctly before any result is received
         let (stop_tx, url) = start_mock_release_server("99.0.0");
+        let pool = WorkerPool::new(1, None);
 
         // Use a very long interval so we only test the initial state
-        let checker = start_periodic_update_check_with_interval(&url, Duration::from_secs(3600));
+        let checker = start_periodic_update_check_with_interval(
+            &pool,
+            &url,
+            Duration::from_secs(3600),
+            RealTimeSource::shared(),
+        );
 
         // Immediately check (before result arrives)
         assert!(!checker.is_update_available());
@@ -752,4 +1354,187 @@ mod tests {
         drop(checker);
         let _ = stop_tx.send(());
     }
+
+    /// Test helper: serve `body` at any path except `*.sha256`, which is not
+    /// found, answering `Range` requests if `honor_range`
+    ///
+    /// Returns (stop_sender, url of the file, Range headers received)
+    fn start_file_server(
+        body: Vec<u8>,
+        honor_range: bool,
+    ) -> (
+        std_mpsc::Sender<()>,
+        String,
+        std::sync::Arc<std::sync::Mutex<Vec<Option<String>>>>,
+    ) {
+        let server = tiny_http::Server::http("127.0.0.1:0").expect("Failed to start test server");
+        let port = server.server_addr().to_ip().unwrap().port();
+        let url = format!("http://127.0.0.1:{}/v1.0.0/fresh-editor.tar.xz", port);
+        let ranges = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+
+        let (stop_tx, stop_rx) = std_mpsc::channel::<()>();
+        let seen = ranges.clone();
+        thread::spawn(move || loop {
+            if stop_rx.try_recv().is_ok() {
+                break;
+            }
+            match server.recv_timeout(Duration::from_millis(100)) {
+                Ok(Some(request)) => {
+                    if request.url().ends_with(".sha256") {
+                        let _ = request.respond(tiny_http::Response::empty(404));
+                        continue;
+                    }
+                    let range = request
+                        .headers()
+                        .iter()
+                        .find(|h| h.field.equiv("Range"))
+                        .map(|h| h.value.as_str().to_string());
+                    seen.lock().unwrap().push(range.clone());
+                    let start = range.filter(|_| honor_range).and_then(|r| {
+                        r.strip_prefix("bytes=")?
+                            .strip_suffix('-')?
+                            .parse::<usize>()
+                            .ok()
+                    });
+                    let response = match start {
+                        Some(start) => {
+                            let content_range =
+                                format!("bytes {}-{}/{}", start, body.len() - 1, body.len());
+                            tiny_http::Response::from_data(body[start..].to_vec())
+                                .with_status_code(206)
+                                .with_header(
+                                    tiny_http::Header::from_bytes(
+                                        &b"Content-Range"[..],
+                                        content_range.as_bytes(),
+                                    )
+                                    .unwrap(),
+                                )
+                        }
+                        None => tiny_http::Response::from_data(body.clone()),
+                    };
+                    let _ = request.respond(response);
+                }
+                Ok(None) => {}
+                Err(_) => break,
+            }
+        });
+
+        (stop_tx, url, ranges)
+    }
+
+    #[test]
+    fn test_download_resumes_partial_file() {
+        let body: Vec<u8> = (0..100_000u32).map(|i| (i % 251) as u8).collect();
+        let (stop_tx, url, ranges) = start_file_server(body.clone(), true);
+        let temp = tempfile::tempdir().unwrap();
+        let dest = temp.path().join("fresh-editor.tar.xz");
+        fs::write(with_suffix(&dest, PARTIAL_SUFFIX), &body[..40_000]).unwrap();
+
+        let mut last = None;
+        download_resumable(&url, &dest, &mut |received, total| {
+            last = Some((received, total))
+        })
+        .unwrap();
+
+        assert_eq!(fs::read(&dest).unwrap(), body);
+        assert!(!with_suffix(&dest, PARTIAL_SUFFIX).exists());
+        assert_eq!(
+            *ranges.lock().unwrap(),
+            vec![Some("bytes=40000-".to_string())]
+        );
+        assert_eq!(last, Some((100_000, Some(100_000))));
+        let _ = stop_tx.send(());
+    }
+
+    #[test]
+    fn test_download_restarts_when_range_is_ignored() {
+        let body: Vec<u8> = (0..10_000u32).map(|i| (i % 13) as u8).collect();
+        let (stop_tx, url, _ranges) = start_file_server(body.clone(), false);
+        let temp = tempfile::tempdir().unwrap();
+        let dest = temp.path().join("fresh-editor.tar.xz");
+        fs::write(with_suffix(&dest, PARTIAL_SUFFIX), &body[..4_000]).unwrap();
+
+        download_resumable(&url, &dest, &mut |_, _| {}).unwrap();
+
+        // The whole file replaced the partial one rather than being appended
+        assert_eq!(fs::read(&dest).unwrap(), body);
+        let _ = stop_tx.send(());
+    }
+
+    #[test]
+    fn test_missing_checksum_is_reported() {
+        let (stop_tx, url, _ranges) = start_file_server(b"archive".to_vec(), true);
+        assert_eq!(
+            fetch_checksum(&format!("{}.sha256", url)),
+            Err(SelfUpdateError::ChecksumMissing)
+        );
+        let _ = stop_tx.send(());
+    }
+
+    #[test]
+    fn test_verify_checksum() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join("archive");
+        fs::write(&path, b"hello\n").unwrap();
+        let sha = "5891b5b522d5df086d0ff0b110fbd9d21bb4fc7163af34d08286a2e846f6be03";
+
+        assert_eq!(
+            parse_checksum(&format!("{}  fresh-editor.tar.xz\n", sha.to_uppercase())),
+            Some(sha.to_string())
+        );
+        assert_eq!(parse_checksum("not a hash"), None);
+        assert!(verify_checksum(&path, sha).is_ok());
+        fs::write(&path, b"tampered\n").unwrap();
+        assert!(matches!(
+            verify_checksum(&path, sha),
+            Err(SelfUpdateError::ChecksumMismatch { expected, .. }) if expected == sha
+        ));
+    }
+
+    #[test]
+    fn test_install_binary_replaces_exe() {
+        let temp = tempfile::tempdir().unwrap();
+        let exe = temp.path().join("fresh");
+        let new_binary = temp.path().join("new-fresh");
+        fs::write(&exe, b"old").unwrap();
+        fs::write(&new_binary, b"new").unwrap();
+
+        let outcome = install_binary(&new_binary, &exe).unwrap();
+
+        assert_eq!(outcome, SelfUpdateOutcome::Installed(exe.clone()));
+        assert_eq!(fs::read(&exe).unwrap(), b"new");
+        assert!(!with_suffix(&exe, STAGED_SUFFIX).exists());
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            assert_ne!(fs::metadata(&exe).unwrap().permissions().mode() & 0o111, 0);
+        }
+    }
+
+    #[test]
+    fn test_finish_pending_update() {
+        let temp = tempfile::tempdir().unwrap();
+        let exe = temp.path().join("fresh");
+        fs::write(&exe, b"old").unwrap();
+        fs::write(with_suffix(&exe, OLD_SUFFIX), b"older").unwrap();
+        assert!(!finish_pending_update(&exe));
+        assert!(!with_suffix(&exe, OLD_SUFFIX).exists());
+
+        fs::write(with_suffix(&exe, STAGED_SUFFIX), b"new").unwrap();
+        assert!(finish_pending_update(&exe));
+        assert_eq!(fs::read(&exe).unwrap(), b"new");
+    }
+
+    #[test]
+    fn test_release_asset_names() {
+        assert_eq!(
+            release_asset_name("x86_64-unknown-linux-gnu").as_deref(),
+            Some("fresh-editor-x86_64-unknown-linux-gnu.tar.xz")
+        );
+        assert_eq!(
+            release_asset_name("x86_64-pc-windows-msvc").as_deref(),
+            Some("fresh-editor-x86_64-pc-windows-msvc.zip")
+        );
+        assert_eq!(release_asset_name("riscv64gc-unknown-linux-gnu"), None);
+    }
 }
@@ -0,0 +1,576 @@
+//! Files on other machines, reached through the system `ssh`
+//!
+//! Paths of the form `ssh://[user@]host[:port]/path` are read, written and
+//! listed by running small POSIX shell scripts on the host with `ssh`, so
+//! nothing beyond an ssh client is needed here and a POSIX shell there.
+//! `ssh://host/~/notes.txt` is relative to the remote home directory.
+//!
+//! Host keys, agents and passwords are left to the user's ssh setup. ssh
+//! runs in batch mode, so a host that would ask for a password fails
+//! instead of waiting for input the editor can't give it. With a control
+//! directory, connections to a host are shared through an ssh control
+//! socket, so only the first command pays for the handshake.
+//!
+//! Saves upload the contents to a temporary file beside the target and
+//! move it into place once the upload is complete, so the file is never
+//! left half-written.
+
+use crate::services::file_loader::FileSource;
+use crate::services::file_provider::FileProvider;
+use crate::services::file_saver::{command_error, pipe_to_command, FileSink};
+use crate::services::fs::{FsEntry, FsEntryType};
+use std::ffi::OsStr;
+use std::fmt;
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Child, ChildStdout, Command, Stdio};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// URL scheme of files reached through ssh
+pub const SSH_SCHEME: &str = "ssh";
+
+/// Seconds an idle shared connection is kept open
+const CONTROL_PERSIST_SECS: u32 = 60;
+
+/// A file or directory on a host reached through ssh
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RemotePath {
+    pub user: Option<String>,
+    pub host: String,
+    pub port: Option<u16>,
+    /// Absolute path on the host; `/~/...` is relative to the home directory
+    pub path: String,
+}
+
+impl RemotePath {
+    /// Parse an `ssh://[user@]host[:port]/path` URL
+    pub fn parse(url: &str) -> Option<Self> {
+        let rest = url.strip_prefix("ssh://")?;
+        let (authority, path) = match rest.find('/') {
+            Some(slash) => (&rest[..slash], &rest[slash..]),
+            None => (rest, "/"),
+        };
+        let (user, host_port) = match authority.rsplit_once('@') {
+            Some((user, host_port)) => (Some(user), host_port),
+            None => (None, authority),
+        };
+        // The port follows the last colon, unless that is inside an IPv6 address
+        let (host, port) = match host_port.rsplit_once(':') {
+            Some((host, port)) if !port.contains(']') => (host, Some(port.parse().ok()?)),
+            _ => (host_port, None),
+        };
+        // ssh would take a host or user starting with '-' for an option
+        let bad_user = user.is_some_and(|u| u.is_empty() || u.starts_with('-'));
+        if host.is_empty() || host.starts_with('-') || bad_user {
+            return None;
+        }
+        Some(Self {
+            user: user.map(str::to_string),
+            host: host.to_string(),
+            port,
+            path: normalize(path),
+        })
+    }
+
+    /// Parse a path the editor holds for a remote file
+    pub fn from_path(path: &Path) -> io::Result<Self> {
+        path.to_str().and_then(Self::parse).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("not an ssh://host/path location: {}", path.display()),
+            )
+        })
+    }
+
+    /// The URL as the path the editor keys the buffer by
+    pub fn to_path_buf(&self) -> PathBuf {
+        PathBuf::from(self.to_string())
+    }
+
+    /// The entry `name` in this directory
+    pub fn join(&self, name: &str) -> Self {
+        let mut joined = self.clone();
+        joined.path = normalize(&format!("{}/{}", self.path, name));
+        joined
+    }
+
+    /// The directory containing this path, or None at the root
+    pub fn parent(&self) -> Option<Self> {
+        if self.path == "/" || self.path == "/~" {
+            return None;
+        }
+        let (parent, _) = self.path.rsplit_once('/')?;
+        let mut path = self.clone();
+        path.path = if parent.is_empty() {
+            "/".to_string()
+        } else {
+            parent.to_string()
+        };
+        Some(path)
+    }
+
+    /// Name of the last path component, or the host for the root
+    pub fn name(&self) -> &str {
+        match self.path.rsplit_once('/') {
+            Some((_, name)) if !name.is_empty() => name,
+            _ => &self.host,
+        }
+    }
+
+    /// The path as passed to the remote shell
+    fn remote_arg(&self) -> String {
+        match self.path.strip_prefix("/~") {
+            Some("") => ".".to_string(),
+            Some(rest) if rest.starts_with('/') => format!(".{}", rest),
+            _ => self.path.clone(),
+        }
+    }
+
+    /// Host as ssh expects it, without the brackets around IPv6 addresses
+    fn ssh_host(&self) -> &str {
+        self.host.trim_start_matches('[').trim_end_matches(']')
+    }
+}
+
+impl fmt::Display for RemotePath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "ssh://")?;
+        if let Some(user) = &self.user {
+            write!(f, "{}@", user)?;
+        }
+        write!(f, "{}", self.host)?;
+        if let Some(port) = self.port {
+            write!(f, ":{}", port)?;
+        }
+        write!(f, "{}", self.path)
+    }
+}
+
+/// Collapse repeated slashes, `.` and `..` in an absolute path
+fn normalize(path: &str) -> String {
+    let mut parts: Vec<&str> = Vec::new();
+    for part in path.split('/') {
+        match part {
+            "" | "." => {}
+            ".." => {
+                // Stay inside the home directory of `/~/...` paths
+                if parts.last().is_some_and(|last| *last != "~") {
+                    parts.pop();
+                }
+            }
+            part => parts.push(part),
+        }
+    }
+    format!("/{}", parts.join("/"))
+}
+
+/// Quote `text` as a single word for a POSIX shell
+pub fn shell_quote(text: &str) -> String {
+    format!("'{}'", text.replace('\'', r"'\''"))
+}
+
+/// Reads, writes and lists files on hosts reached through `ssh`
+#[derive(Debug, Clone, Default)]
+pub struct SshFileProvider {
+    /// Arguments passed to every `ssh` before the editor's own, so they
+    /// take precedence
+    args: Vec<String>,
+    /// Directory for the control sockets of shared connections; None runs
+    /// every command over a connection of its own
+    control_dir: Option<PathBuf>,
+}
+
+impl SshFileProvider {
+    pub fn new(args: Vec<String>, control_dir: Option<PathBuf>) -> Self {
+        let control_dir = control_dir.filter(|dir| match create_private_dir(dir) {
+            Ok(()) => true,
+            Err(e) => {
+                tracing::warn!(
+                    "Not sharing ssh connections, can't create {}: {}",
+                    dir.display(),
+                    e
+                );
+                false
+            }
+        });
+        Self { args, control_dir }
+    }
+
+    /// `ssh` running `script` with `sh` on the host, the remote path as `$1`
+    /// and `extra` as the following arguments
+    fn command(&self, remote: &RemotePath, script: &str, extra: &[&str]) -> Command {
+        let mut command = Command::new("ssh");
+        command.args(&self.args).args(["-o", "BatchMode=yes"]);
+        if let Some(dir) = &self.control_dir {
+            command
+                .args(["-o", "ControlMaster=auto"])
+                .arg("-o")
+                .arg(format!("ControlPath={}", dir.join("%C").display()))
+                .arg("-o")
+                .arg(format!("ControlPersist={}", CONTROL_PERSIST_SECS));
+        }
+        if let Some(port) = remote.port {
+            command.arg("-p").arg(port.to_string());
+        }
+        if let Some(user) = &remote.user {
+            command.arg("-l").arg(user);
+        }
+        let mut line = format!(
+            "sh -c {} sh {}",
+            shell_quote(script),
+            shell_quote(&remote.remote_arg())
+        );
+        for arg in extra {
+            line.push(' ');
+            line.push_str(&shell_quote(arg));
+        }
+        command.arg(remote.ssh_host()).arg(line);
+        command
+    }
+
+    /// Run `script` on the host and collect what it prints
+    fn run(&self, remote: &RemotePath, script: &str, extra: &[&str]) -> io::Result<Vec<u8>> {
+        let mut command = self.command(remote, script, extra);
+        let output = command
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .output()
+            .map_err(spawn_error)?;
+        if !output.status.success() {
+            return Err(command_error(
+                command.get_program(),
+                &output.stderr,
+                output.status,
+            ));
+        }
+        Ok(output.stdout)
+    }
+}
+
+impl FileSource for SshFileProvider {
+    fn size(&self, path: &Path) -> io::Result<u64> {
+        let remote = RemotePath::from_path(path)?;
+        let output = self.run(&remote, r#"wc -c < "$1""#, &[])?;
+        String::from_utf8_lossy(&output)
+            .trim()
+            .parse()
+            .map_err(|_| io::Error::other(format!("unexpected size of {}", remote)))
+    }
+
+    fn open(&self, path: &Path) -> io::Result<Box<dyn Read + Send>> {
+        let remote = RemotePath::from_path(path)?;
+        let mut child = self
+            .command(&remote, r#"exec cat -- "$1""#, &[])
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(spawn_error)?;
+        let stdout = child
+            .stdout
+            .take()
+            .ok_or_else(|| io::Error::other("ssh has no output"))?;
+        Ok(Box::new(CommandReader {
+            child,
+            stdout,
+            finished: false,
+        }))
+    }
+}
+
+impl FileSink for SshFileProvider {
+    fn replace(
+        &self,
+        path: &Path,
+        contents: &mut dyn FnMut(&mut dyn Write) -> io::Result<()>,
+    ) -> io::Result<u64> {
+        static NEXT_SAVE: AtomicU64 = AtomicU64::new(1);
+
+        let remote = RemotePath::from_path(path)?;
+        let temp = format!(
+            "{}.fresh-save-{}-{}",
+            remote.remote_arg(),
+            std::process::id(),
+            NEXT_SAVE.fetch_add(1, Ordering::Relaxed)
+        );
+        // Copying the file first gives the upload its mode
+        let upload = r#"if [ -e "$1" ]; then cp -p -- "$1" "$2" || exit 1; fi; exec cat > "$2""#;
+        let uploaded =
+            pipe_to_command(self.command(&remote, upload, &[&temp]), contents).map_err(spawn_error);
+        let result = uploaded.and_then(|size| {
+            self.run(&remote, r#"mv -f -- "$2" "$1""#, &[&temp])?;
+            Ok(size)
+        });
+        if result.is_err() {
+            let _ = self.run(&remote, r#"rm -f -- "$2""#, &[&temp]);
+        }
+        result
+    }
+}
+
+impl FileProvider for SshFileProvider {
+    fn entry_type(&self, path: &Path) -> io::Result<Option<FsEntryType>> {
+        let remote = RemotePath::from_path(path)?;
+        let output = self.run(
+            &remote,
+            r#"if [ -d "$1" ]; then echo d; elif [ -e "$1" ]; then echo f; fi"#,
+            &[],
+        )?;
+        Ok(match String::from_utf8_lossy(&output).trim() {
+            "d" => Some(FsEntryType::Directory),
+            "f" => Some(FsEntryType::File),
+            _ => None,
+        })
+    }
+
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<FsEntry>> {
+        let remote = RemotePath::from_path(path)?;
+        let script = r#"cd -- "$1" || exit 1
+for f in * .[!.]* ..?*; do
+  if [ -d "$f" ]; then printf 'd %s\0' "$f"
+  elif [ -e "$f" ] || [ -L "$f" ]; then printf 'f %s\0' "$f"
+  fi
+done"#;
+        let output = self.run(&remote, script, &[])?;
+        Ok(parse_listing(&output, &remote))
+    }
+}
+
+/// Entries of `dir` from the listing script's output: a type letter, a
+/// space and the name, for each entry, each ended by a NUL byte
+fn parse_listing(output: &[u8], dir: &RemotePath) -> Vec<FsEntry> {
+    output
+        .split(|&b| b == 0)
+        .filter_map(|line| {
+            let line = String::from_utf8_lossy(line);
+            let (kind, name) = line.split_once(' ')?;
+            let entry_type = match kind {
+                "d" => FsEntryType::Directory,
+                "f" => FsEntryType::File,
+                _ => return None,
+            };
+            Some(FsEntry::new(
+                dir.join(name).to_path_buf(),
+                name.to_string(),
+                entry_type,
+            ))
+        })
+        .collect()
+}
+
+/// Explain a failure to start `ssh`
+fn spawn_error(error: io::Error) -> io::Error {
+    if error.kind() == io::ErrorKind::NotFound {
+        io::Error::new(io::ErrorKind::NotFound, "ssh is not installed")
+    } else {
+        error
+    }
+}
+
+/// Create a directory only its owner can use
+fn create_private_dir(dir: &Path) -> io::Result<()> {
+    let mut builder = std::fs::DirBuilder::new();
+    builder.recursive(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::DirBuilderExt;
+        builder.mode(0o700);
+    }
+    builder.create(dir)
+}
+
+/// Output of a command, failing at the end if the command did
+///
+/// Dropping it before the end kills the command.
+struct CommandReader {
+    child: Child,
+    stdout: ChildStdout,
+    finished: bool,
+}
+
+impl CommandReader {
+    fn finish(&mut self) -> io::Result<()> {
+        self.finished = true;
+        let mut stderr = Vec::new();
+        if let Some(mut pipe) = self.child.stderr.take() {
+            let _ = pipe.read_to_end(&mut stderr);
+        }
+        let status = self.child.wait()?;
+        if !status.success() {
+            return Err(command_error(OsStr::new("ssh"), &stderr, status));
+        }
+        Ok(())
+    }
+}
+
+impl Read for CommandReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.finished {
+            return Ok(0);
+        }
+        let n = self.stdout.read(buf)?;
+        if n == 0 && !buf.is_empty() {
+            self.finish()?;
+        }
+        Ok(n)
+    }
+}
+
+impl Drop for CommandReader {
+    fn drop(&mut self) {
+        if !self.finished {
+            let _ = self.child.kill();
+            let _ = self.child.wait();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_remote_path() {
+        let remote = RemotePath::parse("ssh://me@example.com:2222/etc/hosts").unwrap();
+        assert_eq!(remote.user.as_deref(), Some("me"));
+        assert_eq!(remote.host, "example.com");
+        assert_eq!(remote.port, Some(2222));
+        assert_eq!(remote.path, "/etc/hosts");
+        assert_eq!(remote.to_string(), "ssh://me@example.com:2222/etc/hosts");
+
+        let remote = RemotePath::parse("ssh://[::1]/tmp//a/./b/../c").unwrap();
+        assert_eq!(remote.host, "[::1]");
+        assert_eq!(remote.ssh_host(), "::1");
+        assert_eq!(remote.port, None);
+        assert_eq!(remote.path, "/tmp/a/c");
+
+        assert_eq!(RemotePath::parse("ssh://host").unwrap().path, "/");
+        assert!(RemotePath::parse("ssh:///etc").is_none());
+        assert!(RemotePath::parse("ssh://-oProxyCommand=x/etc").is_none());
+        assert!(RemotePath::parse("ssh://host:port/etc").is_none());
+        assert!(RemotePath::parse("/etc/hosts").is_none());
+    }
+
+    #[test]
+    fn test_remote_path_navigation() {
+        let dir = RemotePath::parse("ssh://host/etc").unwrap();
+        assert_eq!(dir.join("hosts").to_string(), "ssh://host/etc/hosts");
+        assert_eq!(dir.join("..").to_string(), "ssh://host/");
+        assert_eq!(dir.parent().unwrap().to_string(), "ssh://host/");
+        assert!(dir.parent().unwrap().parent().is_none());
+        assert_eq!(dir.name(), "etc");
+        assert_eq!(dir.parent().unwrap().name(), "host");
+    }
+
+    #[test]
+    fn test_home_relative_paths() {
+        let home = RemotePath::parse("ssh://host/~").unwrap();
+        assert_eq!(home.remote_arg(), ".");
+        assert!(home.parent().is_none());
+        assert_eq!(home.join("..").path, "/~");
+        let file = home.join("notes.txt");
+        assert_eq!(file.to_string(), "ssh://host/~/notes.txt");
+        assert_eq!(file.remote_arg(), "./notes.txt");
+    }
+
+    #[test]
+    fn test_shell_quote() {
+        assert_eq!(shell_quote("a b"), "'a b'");
+        assert_eq!(shell_quote("it's"), r"'it'\''s'");
+    }
+
+    #[test]
+    fn test_command_line() {
+        let provider = SshFileProvider::new(vec!["-F".into(), "cfg".into()], None);
+        let remote = RemotePath::parse("ssh://me@host:2222/a b").unwrap();
+        let command = provider.command(&remote, r#"cat "$1""#, &["x"]);
+        let args: Vec<_> = command
+            .get_args()
+            .map(|a| a.to_string_lossy().into_owned())
+            .collect();
+        assert_eq!(
+            args,
+            vec![
+                "-F",
+                "cfg",
+                "-o",
+                "BatchMode=yes",
+                "-p",
+                "2222",
+                "-l",
+                "me",
+                "host",
+                r#"sh -c 'cat "$1"' sh '/a b' 'x'"#,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_listing() {
+        let dir = RemotePath::parse("ssh://host/etc").unwrap();
+        let entries = parse_listing(b"d ssh\0f hosts\0f with space\0", &dir);
+        let listed: Vec<_> = entries
+            .iter()
+            .map(|e| {
+                (
+                    e.name.as_str(),
+                    e.is_dir(),
+                    e.path.to_string_lossy().into_owned(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            listed,
+            vec![
+                ("ssh", true, "ssh://host/etc/ssh".to_string()),
+                ("hosts", false, "ssh://host/etc/hosts".to_string()),
+                ("with space", false, "ssh://host/etc/with space".to_string()),
+            ]
+        );
+    }
+
+    /// Exercises a real ssh connection; set `FRESH_TEST_SSH_HOST` (e.g. to
+    /// `localhost`) to a host you can log in to without a password
+    #[test]
+    fn test_round_trip_over_real_ssh() {
+        let Ok(host) = std::env::var("FRESH_TEST_SSH_HOST") else {
+            return;
+        };
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let local = temp_dir.path().join("remote.txt");
+        std::fs::write(&local, "before\n").unwrap();
+        let url = format!("ssh://{}{}", host, local.display());
+        let path = PathBuf::from(&url);
+        let provider = SshFileProvider::new(Vec::new(), Some(temp_dir.path().join("ssh")));
+
+        assert_eq!(provider.entry_type(&path).unwrap(), Some(FsEntryType::File));
+        assert_eq!(provider.size(&path).unwrap(), 7);
+        let mut contents = String::new();
+        provider
+            .open(&path)
+            .unwrap()
+            .read_to_string(&mut contents)
+            .unwrap();
+        assert_eq!(contents, "before\n");
+
+        let size = provider
+            .replace(&path, &mut |out| out.write_all(b"after\n"))
+            .unwrap();
+        assert_eq!(size, 6);
+        assert_eq!(std::fs::read_to_string(&local).unwrap(), "after\n");
+
+        let dir = PathBuf::from(format!("ssh://{}{}", host, temp_dir.path().display()));
+        let names: Vec<_> = provider
+            .read_dir(&dir)
+            .unwrap()
+            .into_iter()
+            .map(|e| e.name)
+            .collect();
+        assert!(names.contains(&"remote.txt".to_string()));
+        assert!(provider
+            .open(&PathBuf::from(format!("{}.missing", url)))
+            .and_then(|mut r| r.read_to_end(&mut Vec::new()))
+            .is_err());
+    }
+}
@@ -0,0 +1,450 @@
+//! Size-limited log file written from a background thread
+//!
+//! Tracing writes each formatted event to a [`RotatingLog`], which queues it
+//! for a writer thread and returns at once, so a slow disk never stalls the
+//! editor. When the queue is full, events are dropped and counted; the writer
+//! notes how many were lost once it catches up.
+//!
+//! When the next event would take the file past its maximum size, the writer
+//! thread renames it to `<path>.1`, shifting older copies to `<path>.2` and so
+//! on, deletes copies past the configured number and starts a new file. Only
+//! that thread touches the file, so rotation is safe however many threads log
+//! at the same time.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Receiver, SyncSender, TrySendError};
+use std::sync::Arc;
+use std::time::Duration;
+
+use tracing_subscriber::fmt::MakeWriter;
+
+/// Events waiting for the writer thread before new ones are dropped
+const QUEUE_LEN: usize = 8192;
+
+/// Longest [`RotatingLog::flush`] waits for the writer thread
+const FLUSH_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Bytes in a megabyte, the unit of `log.max_size_mb`
+pub const MEGABYTE: u64 = 1024 * 1024;
+
+/// How large the log grows and how many rotated copies are kept
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LogLimits {
+    /// Size in bytes at which the file is rotated; 0 never rotates it
+    pub max_size: u64,
+    /// Rotated copies kept beside the current file
+    pub max_files: usize,
+}
+
+impl Default for LogLimits {
+    fn default() -> Self {
+        Self {
+            max_size: 20 * MEGABYTE,
+            max_files: 3,
+        }
+    }
+}
+
+/// What the writer thread is asked to do
+enum WriterMessage {
+    /// Append a formatted event
+    Write(Vec<u8>),
+    /// Write out everything queued so far, then answer
+    Flush(mpsc::Sender<()>),
+}
+
+/// State shared between the log handles and the writer thread
+struct Shared {
+    path: PathBuf,
+    max_size: AtomicU64,
+    max_files: AtomicUsize,
+    /// Events dropped because the queue was full
+    dropped: AtomicU64,
+}
+
+impl Shared {
+    fn limits(&self) -> LogLimits {
+        LogLimits {
+            max_size: self.max_size.load(Ordering::Relaxed),
+            max_files: self.max_files.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// A log file that rotates at a size limit, written from a background thread
+///
+/// Clones write to the same file. Use it as the writer of a tracing `fmt`
+/// layer; each event is queued as one message, so lines from different
+/// threads never interleave.
+#[derive(Clone)]
+pub struct RotatingLog {
+    sender: SyncSender<WriterMessage>,
+    shared: Arc<Shared>,
+}
+
+/// `<path>.<index>`, the `index`th rotated copy of the log at `path`
+pub fn rotated_path(path: &Path, index: usize) -> PathBuf {
+    let mut name = path.as_os_str().to_os_string();
+    name.push(format!(".{}", index));
+    PathBuf::from(name)
+}
+
+impl RotatingLog {
+    /// Create or truncate the log at `path` and start its writer thread
+    pub fn create(path: &Path, limits: LogLimits) -> io::Result<Self> {
+        let file = File::create(path)?;
+        let shared = Arc::new(Shared {
+            path: path.to_path_buf(),
+            max_size: AtomicU64::new(limits.max_size),
+            max_files: AtomicUsize::new(limits.max_files),
+            dropped: AtomicU64::new(0),
+        });
+        let (sender, receiver) = mpsc::sync_channel(QUEUE_LEN);
+        let writer = Writer {
+            file: BufWriter::new(file),
+            size: 0,
+            shared: shared.clone(),
+        };
+        std::thread::Builder::new()
+            .name("log-writer".to_string())
+            .spawn(move || writer.run(receiver))?;
+        Ok(Self { sender, shared })
+    }
+
+    /// Path of the current log file
+    pub fn path(&self) -> &Path {
+        &self.shared.path
+    }
+
+    /// Size limit and number of rotated copies in effect
+    pub fn limits(&self) -> LogLimits {
+        self.shared.limits()
+    }
+
+    /// Change the size limit and number of rotated copies; applies from the
+    /// next event written
+    pub fn set_limits(&self, limits: LogLimits) {
+        self.shared
+            .max_size
+            .store(limits.max_size, Ordering::Relaxed);
+        self.shared
+            .max_files
+            .store(limits.max_files, Ordering::Relaxed);
+    }
+
+    /// Wait until the events queued so far are in the file
+    ///
+    /// Gives up after a second, so a stuck disk can't hang the caller.
+    pub fn flush(&self) {
+        let (done, wait) = mpsc::channel();
+        if self.sender.send(WriterMessage::Flush(done)).is_ok() {
+            let _ = wait.recv_timeout(FLUSH_TIMEOUT);
+        }
+    }
+}
+
+impl Write for &RotatingLog {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self.sender.try_send(WriterMessage::Write(buf.to_vec())) {
+            Ok(()) | Err(TrySendError::Disconnected(_)) => {}
+            Err(TrySendError::Full(_)) => {
+                self.shared.dropped.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl<'a> MakeWriter<'a> for RotatingLog {
+    type Writer = &'a RotatingLog;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        self
+    }
+}
+
+/// The writer thread's end: the open file and its size
+struct Writer {
+    file: BufWriter<File>,
+    size: u64,
+    shared: Arc<Shared>,
+}
+
+impl Writer {
+    /// Write queued events until every [`RotatingLog`] is dropped, flushing
+    /// whenever the queue runs empty
+    fn run(mut self, receiver: Receiver<WriterMessage>) {
+        while let Ok(message) = receiver.recv() {
+            self.handle(message);
+            while let Ok(message) = receiver.try_recv() {
+                self.handle(message);
+            }
+            let _ = self.file.flush();
+        }
+    }
+
+    fn handle(&mut self, message: WriterMessage) {
+        match message {
+            WriterMessage::Write(bytes) => {
+                let dropped = self.shared.dropped.swap(0, Ordering::Relaxed);
+                if dropped > 0 {
+                    let note = format!(
+                        "... {} log events dropped because the log writer fell behind\n",
+                        dropped
+                    );
+                    self.write(note.as_bytes());
+                }
+                self.write(&bytes);
+            }
+            WriterMessage::Flush(done) => {
+                let _ = self.file.flush();
+                let _ = done.send(());
+            }
+        }
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        let max_size = self.shared.max_size.load(Ordering::Relaxed);
+        if max_size > 0 && self.size > 0 && self.size + bytes.len() as u64 > max_size {
+            self.rotate();
+        }
+        if self.file.write_all(bytes).is_ok() {
+            self.size += bytes.len() as u64;
+        }
+    }
+
+    /// Shift the rotated copies up by one, move the current file to `.1` and
+    /// start a new one
+    fn rotate(&mut self) {
+        let _ = self.file.flush();
+        let path = &self.shared.path;
+        let max_files = self.shared.max_files.load(Ordering::Relaxed);
+
+        // The oldest copy and any left by a higher limit would go past it
+        let mut index = max_files.max(1);
+        while rotated_path(path, index).exists() {
+            let _ = fs::remove_file(rotated_path(path, index));
+            index += 1;
+        }
+        for index in (1..max_files).rev() {
+            let from = rotated_path(path, index);
+            if from.exists() {
+                let _ = fs::rename(&from, rotated_path(path, index + 1));
+            }
+        }
+
+        let file = if max_files > 0 {
+            let _ = fs::rename(path, rotated_path(path, 1));
+            File::create(path)
+        } else {
+            OpenOptions::new()
+                .write(true)
+                .truncate(true)
+                .create(true)
+                .open(path)
+        };
+        match file {
+            Ok(file) => {
+                self.file = BufWriter::new(file);
+                self.size = 0;
+            }
+            Err(e) => {
+                // Keep appending to the old file rather than losing events
+                eprintln!("Failed to rotate log {:?}: {}", path, e);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn write_line(log: &RotatingLog, line: &str) {
+        let mut writer = log.make_writer();
+        writer.write_all(line.as_bytes()).unwrap();
+    }
+
+    fn read(path: &Path) -> String {
+        fs::read_to_string(path).unwrap_or_default()
+    }
+
+    #[test]
+    fn test_rotated_path() {
+        assert_eq!(
+            rotated_path(Path::new("/logs/fresh-1.log"), 2),
+            PathBuf::from("/logs/fresh-1.log.2")
+        );
+    }
+
+    #[test]
+    fn test_rotation_shifts_copies_and_keeps_max_files() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("fresh.log");
+        let log = RotatingLog::create(
+            &path,
+            LogLimits {
+                max_size: 20,
+                max_files: 2,
+            },
+        )
+        .unwrap();
+
+        for i in 0..5 {
+            write_line(&log, &format!("line {} of the log\n", i));
+        }
+        log.flush();
+
+        assert_eq!(read(&path), "line 4 of the log\n");
+        assert_eq!(read(&rotated_path(&path, 1)), "line 3 of the log\n");
+        assert_eq!(read(&rotated_path(&path, 2)), "line 2 of the log\n");
+        assert!(!rotated_path(&path, 3).exists());
+    }
+
+    #[test]
+    fn test_lines_fill_file_up_to_max_size() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("fresh.log");
+        let log = RotatingLog::create(
+            &path,
+            LogLimits {
+                max_size: 12,
+                max_files: 1,
+            },
+        )
+        .unwrap();
+
+        for line in ["aaaa\n", "bbbb\n", "cccc\n"] {
+            write_line(&log, line);
+        }
+        log.flush();
+
+        assert_eq!(read(&rotated_path(&path, 1)), "aaaa\nbbbb\n");
+        assert_eq!(read(&path), "cccc\n");
+    }
+
+    #[test]
+    fn test_lowering_limits_removes_extra_copies() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("fresh.log");
+        let log = RotatingLog::create(
+            &path,
+            LogLimits {
+                max_size: 1,
+                max_files: 3,
+            },
+        )
+        .unwrap();
+        for i in 0..4 {
+            write_line(&log, &format!("{}\n", i));
+        }
+        log.flush();
+        assert!(rotated_path(&path, 3).exists());
+
+        log.set_limits(LogLimits {
+            max_size: 1,
+            max_files: 0,
+        });
+        write_line(&log, "last\n");
+        log.flush();
+
+        assert_eq!(read(&path), "last\n");
+        for index in 1..=3 {
+            assert!(!rotated_path(&path, index).exists(), "copy {}", index);
+        }
+    }
+
+    #[test]
+    fn test_zero_max_size_never_rotates() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("fresh.log");
+        let log = RotatingLog::create(
+            &path,
+            LogLimits {
+                max_size: 0,
+                max_files: 3,
+            },
+        )
+        .unwrap();
+        for _ in 0..100 {
+            write_line(&log, "0123456789\n");
+        }
+        log.flush();
+
+        assert_eq!(read(&path).len(), 1100);
+        assert!(!rotated_path(&path, 1).exists());
+    }
+
+    #[test]
+    fn test_concurrent_logging_keeps_lines_whole() {
+        use tracing_subscriber::prelude::*;
+
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("fresh.log");
+        let log = RotatingLog::create(
+            &path,
+            LogLimits {
+                max_size: 4096,
+                max_files: 1000,
+            },
+        )
+        .unwrap();
+        let subscriber = tracing_subscriber::registry().with(
+            tracing_subscriber::fmt::layer()
+                .with_ansi(false)
+                .with_writer(log.clone()),
+        );
+        let dispatch = tracing::Dispatch::new(subscriber);
+
+        let threads: Vec<_> = (0..4)
+            .map(|thread| {
+                let dispatch = dispatch.clone();
+                std::thread::spawn(move || {
+                    tracing::dispatcher::with_default(&dispatch, || {
+                        for i in 0..250 {
+                            tracing::info!("thread {} event {} end", thread, i);
+                        }
+                    })
+                })
+            })
+            .collect();
+        for thread in threads {
+            thread.join().unwrap();
+        }
+        log.flush();
+
+        let mut contents = String::new();
+        let mut index = 1;
+        while rotated_path(&path, index).exists() {
+            contents.insert_str(0, &read(&rotated_path(&path, index)));
+            index += 1;
+        }
+        contents.push_str(&read(&path));
+        assert!(index > 2, "log should have been rotated");
+
+        let lines: Vec<&str> = contents.lines().collect();
+        assert_eq!(lines.len(), 1000);
+        for line in &lines {
+            assert!(line.ends_with(" end"), "broken line: {:?}", line);
+        }
+        for thread in 0..4 {
+            for i in 0..250 {
+                let event = format!("thread {} event {} end", thread, i);
+                assert_eq!(
+                    lines.iter().filter(|line| line.ends_with(&event)).count(),
+                    1,
+                    "{}",
+                    event
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,713 @@
+//! Settings archives
+//!
+//! A settings archive is a gzip-compressed tar file holding the user-facing
+//! files from the [`DirectoryContext`] locations, grouped into
+//! [`ArchiveCategory`]s, with a `manifest.json` first that names the format
+//! version, the categories and the files. Paths inside the archive start with
+//! `config/` or `state/` and are relative to the config and state
+//! directories, so an archive made on one platform imports on another.
+//!
+//! "Export Settings Archive" and `--export-settings` write one;
+//! "Import Settings Archive" and `--import-settings` read one back through an
+//! [`ImportPlan`], which copies every file it replaces into a backup
+//! directory before writing anything. Keybindings and keymaps live in the
+//! config file and travel with it.
+
+use std::collections::BTreeSet;
+use std::io::{self, Read};
+use std::path::{Component, Path, PathBuf};
+
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+
+use crate::config_io::DirectoryContext;
+
+/// Name of the manifest entry, first in every archive
+pub const MANIFEST_NAME: &str = "manifest.json";
+
+/// `format` of the manifest
+pub const ARCHIVE_FORMAT: &str = "fresh-settings";
+
+/// Current archive version; archives from newer versions are refused
+pub const ARCHIVE_VERSION: u32 = 1;
+
+/// File name offered when exporting
+pub const DEFAULT_FILE_NAME: &str = "fresh-settings.tar.gz";
+
+/// A group of files that is exported and imported together
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ArchiveCategory {
+    /// The user config file, including keybindings and keymaps
+    Config,
+    Themes,
+    Grammars,
+    Plugins,
+    /// Named layouts of every project
+    Layouts,
+    /// Sessions and per-file cursor and scroll positions
+    Sessions,
+    /// Search and replace history and recent projects
+    History,
+}
+
+/// Where each category's files are, as archive paths: either a file or a
+/// directory taken whole. A path belongs to the category of the longest root
+/// it falls under, so layouts are kept out of sessions.
+const ROOTS: &[(ArchiveCategory, &str)] = &[
+    (ArchiveCategory::Config, "config/config.json"),
+    (ArchiveCategory::Themes, "config/themes"),
+    (ArchiveCategory::Grammars, "config/grammars"),
+    (ArchiveCategory::Plugins, "config/plugins"),
+    (ArchiveCategory::Layouts, "state/sessions/layouts"),
+    (ArchiveCategory::Sessions, "state/sessions"),
+    (ArchiveCategory::Sessions, "state/file_states"),
+    (ArchiveCategory::History, "state/search_history.json"),
+    (ArchiveCategory::History, "state/replace_history.json"),
+    (ArchiveCategory::History, "state/recent_projects.json"),
+];
+
+impl ArchiveCategory {
+    /// Every category, in the order they are listed
+    pub const ALL: [ArchiveCategory; 7] = [
+        ArchiveCategory::Config,
+        ArchiveCategory::Themes,
+        ArchiveCategory::Grammars,
+        ArchiveCategory::Plugins,
+        ArchiveCategory::Layouts,
+        ArchiveCategory::Sessions,
+        ArchiveCategory::History,
+    ];
+
+    /// Name used in the manifest and on the command line
+    pub fn name(self) -> &'static str {
+        match self {
+            ArchiveCategory::Config => "config",
+            ArchiveCategory::Themes => "themes",
+            ArchiveCategory::Grammars => "grammars",
+            ArchiveCategory::Plugins => "plugins",
+            ArchiveCategory::Layouts => "layouts",
+            ArchiveCategory::Sessions => "sessions",
+            ArchiveCategory::History => "history",
+        }
+    }
+
+    /// The category called `name`
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|category| category.name() == name)
+    }
+
+    /// Whether exports include the category unless told otherwise
+    ///
+    /// Sessions and histories name the files and projects worked on and hold
+    /// searched text, so they only go into an archive when asked for.
+    pub fn included_by_default(self) -> bool {
+        !matches!(self, ArchiveCategory::Sessions | ArchiveCategory::History)
+    }
+
+    /// Whether imported files of this category only take effect after a
+    /// restart
+    pub fn needs_restart(self) -> bool {
+        matches!(
+            self,
+            ArchiveCategory::Config | ArchiveCategory::Grammars | ArchiveCategory::Plugins
+        )
+    }
+
+    /// The categories exported when none are named
+    pub fn defaults() -> BTreeSet<ArchiveCategory> {
+        Self::ALL
+            .into_iter()
+            .filter(|category| category.included_by_default())
+            .collect()
+    }
+
+    /// Parse a comma-separated list of category names
+    pub fn parse_list(list: &str) -> Result<BTreeSet<ArchiveCategory>, SettingsArchiveError> {
+        list.split(',')
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .map(|name| {
+                Self::from_name(name)
+                    .ok_or_else(|| SettingsArchiveError::UnknownCategory(name.to_string()))
+            })
+            .collect()
+    }
+}
+
+/// The category an archive path belongs to, if any
+pub fn category_of(archive_path: &str) -> Option<ArchiveCategory> {
+    ROOTS
+        .iter()
+        .filter(|(_, root)| {
+            archive_path == *root
+                || archive_path
+                    .strip_prefix(root)
+                    .is_some_and(|rest| rest.starts_with('/'))
+        })
+        .max_by_key(|(_, root)| root.len())
+        .map(|(category, _)| *category)
+}
+
+/// Where an archive path goes on this machine
+///
+/// Returns `None` for paths that aren't relative, plain and under the config
+/// or state directory.
+fn local_path(dirs: &DirectoryContext, archive_path: &str) -> Option<PathBuf> {
+    let (base, rest) = archive_path.split_once('/')?;
+    let mut path = match base {
+        "config" => dirs.config_dir.clone(),
+        "state" => dirs.state_dir.clone(),
+        _ => return None,
+    };
+    for part in rest.split('/') {
+        let mut components = Path::new(part).components();
+        match (components.next(), components.next()) {
+            (Some(Component::Normal(name)), None) => path.push(name),
+            _ => return None,
+        }
+    }
+    Some(path)
+}
+
+/// The contents of `manifest.json`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ArchiveManifest {
+    /// Always [`ARCHIVE_FORMAT`]
+    pub format: String,
+    pub version: u32,
+    /// Version of Fresh that wrote the archive
+    pub fresh_version: String,
+    /// Categories chosen when exporting
+    pub categories: Vec<ArchiveCategory>,
+    /// Archive paths of the files, in archive order
+    pub files: Vec<String>,
+}
+
+/// Why an archive couldn't be written or read
+#[derive(Debug)]
+pub enum SettingsArchiveError {
+    Io(io::Error),
+    Json(serde_json::Error),
+    /// The file has no manifest or isn't a settings archive
+    NotAnArchive,
+    /// The archive was written by a newer version of Fresh
+    VersionTooNew {
+        version: u32,
+        max_supported: u32,
+    },
+    /// An entry outside the known locations, or with `..` or an absolute path
+    UnexpectedEntry(String),
+    /// A category name that doesn't exist
+    UnknownCategory(String),
+}
+
+impl std::fmt::Display for SettingsArchiveError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SettingsArchiveError::Io(e) => write!(f, "IO error: {}", e),
+            SettingsArchiveError::Json(e) => write!(f, "Invalid manifest: {}", e),
+            SettingsArchiveError::NotAnArchive => write!(f, "Not a Fresh settings archive"),
+            SettingsArchiveError::VersionTooNew {
+                version,
+                max_supported,
+            } => write!(
+                f,
+                "Archive version {} is newer than supported (max: {})",
+                version, max_supported
+            ),
+            SettingsArchiveError::UnexpectedEntry(path) => {
+                write!(f, "Unexpected file in archive: {}", path)
+            }
+            SettingsArchiveError::UnknownCategory(name) => {
+                write!(
+                    f,
+                    "Unknown category: {} (expected one of: {})",
+                    name,
+                    ArchiveCategory::ALL.map(ArchiveCategory::name).join(", ")
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for SettingsArchiveError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SettingsArchiveError::Io(e) => Some(e),
+            SettingsArchiveError::Json(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for SettingsArchiveError {
+    fn from(e: io::Error) -> Self {
+        SettingsArchiveError::Io(e)
+    }
+}
+
+impl From<serde_json::Error> for SettingsArchiveError {
+    fn from(e: serde_json::Error) -> Self {
+        SettingsArchiveError::Json(e)
+    }
+}
+
+/// Collect the files of `categories`, as (archive path, local path) pairs
+fn collect_files(
+    dirs: &DirectoryContext,
+    categories: &BTreeSet<ArchiveCategory>,
+) -> io::Result<Vec<(String, PathBuf)>> {
+    let mut files = Vec::new();
+    for (category, root) in ROOTS {
+        if !categories.contains(category) {
+            continue;
+        }
+        let Some(local) = local_path(dirs, root) else {
+            continue;
+        };
+        collect_under(root, &local, *category, &mut files)?;
+    }
+    Ok(files)
+}
+
+fn collect_under(
+    archive_path: &str,
+    local: &Path,
+    category: ArchiveCategory,
+    files: &mut Vec<(String, PathBuf)>,
+) -> io::Result<()> {
+    // Symlinks are skipped rather than followed out of the directory
+    let Ok(metadata) = std::fs::symlink_metadata(local) else {
+        return Ok(());
+    };
+    if metadata.is_file() {
+        if category_of(archive_path) == Some(category) {
+            files.push((archive_path.to_string(), local.to_path_buf()));
+        }
+    } else if metadata.is_dir() {
+        let mut entries: Vec<_> = std::fs::read_dir(local)?.collect::<io::Result<_>>()?;
+        entries.sort_by_key(|entry| entry.file_name());
+        for entry in entries {
+            let Some(name) = entry.file_name().to_str().map(str::to_string) else {
+                tracing::warn!("Skipping {:?}: name is not UTF-8", entry.path());
+                continue;
+            };
+            let child = format!("{}/{}", archive_path, name);
+            collect_under(&child, &entry.path(), category, files)?;
+        }
+    }
+    Ok(())
+}
+
+/// Write the files of `categories` to a settings archive at `path`
+///
+/// Returns the manifest written.
+pub fn export(
+    dirs: &DirectoryContext,
+    categories: &BTreeSet<ArchiveCategory>,
+    path: &Path,
+) -> Result<ArchiveManifest, SettingsArchiveError> {
+    let files = collect_files(dirs, categories)?;
+    let manifest = ArchiveManifest {
+        format: ARCHIVE_FORMAT.to_string(),
+        version: ARCHIVE_VERSION,
+        fresh_version: env!("CARGO_PKG_VERSION").to_string(),
+        categories: categories.iter().copied().collect(),
+        files: files.iter().map(|(name, _)| name.clone()).collect(),
+    };
+
+    let encoder = GzEncoder::new(Vec::new(), Compression::default());
+    let mut builder = tar::Builder::new(encoder);
+    let append = |builder: &mut tar::Builder<_>, name: &str, data: &[u8]| {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(data.len() as u64);
+        header.set_mode(0o644);
+        header.set_cksum();
+        builder.append_data(&mut header, name, data)
+    };
+    append(
+        &mut builder,
+        MANIFEST_NAME,
+        &serde_json::to_vec_pretty(&manifest)?,
+    )?;
+    for (name, local) in &files {
+        append(&mut builder, name, &std::fs::read(local)?)?;
+    }
+    let bytes = builder.into_inner()?.finish()?;
+
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, bytes)?;
+    Ok(manifest)
+}
+
+/// A settings archive read into memory
+#[derive(Debug)]
+pub struct SettingsArchive {
+    pub manifest: ArchiveManifest,
+    /// Archive path and contents of each file
+    files: Vec<(String, Vec<u8>)>,
+}
+
+impl SettingsArchive {
+    /// Read and check the archive at `path`
+    pub fn read(path: &Path) -> Result<Self, SettingsArchiveError> {
+        let file = std::fs::File::open(path)?;
+        let mut archive = tar::Archive::new(GzDecoder::new(file));
+        let mut manifest = None;
+        let mut files = Vec::new();
+        let entries = archive
+            .entries()
+            .map_err(|_| SettingsArchiveError::NotAnArchive)?;
+        for entry in entries {
+            let mut entry = entry.map_err(|_| SettingsArchiveError::NotAnArchive)?;
+            let name = entry.path()?.to_string_lossy().replace('\\', "/");
+            if entry.header().entry_type().is_dir() {
+                continue;
+            }
+            let mut data = Vec::new();
+            entry.read_to_end(&mut data)?;
+            if manifest.is_none() {
+                if name != MANIFEST_NAME {
+                    return Err(SettingsArchiveError::NotAnArchive);
+                }
+                manifest = Some(Self::parse_manifest(&data)?);
+                continue;
+            }
+            if !entry.header().entry_type().is_file() || category_of(&name).is_none() {
+                return Err(SettingsArchiveError::UnexpectedEntry(name));
+            }
+            files.push((name, data));
+        }
+        let manifest = manifest.ok_or(SettingsArchiveError::NotAnArchive)?;
+        Ok(Self { manifest, files })
+    }
+
+    /// Check the format and version before reading the rest of the manifest,
+    /// so newer archives are refused with a clear reason
+    fn parse_manifest(data: &[u8]) -> Result<ArchiveManifest, SettingsArchiveError> {
+        let value: serde_json::Value =
+            serde_json::from_slice(data).map_err(|_| SettingsArchiveError::NotAnArchive)?;
+        if value.get("format").and_then(|f| f.as_str()) != Some(ARCHIVE_FORMAT) {
+            return Err(SettingsArchiveError::NotAnArchive);
+        }
+        let version = value.get("version").and_then(|v| v.as_u64()).unwrap_or(0) as u32;
+        if version > ARCHIVE_VERSION {
+            return Err(SettingsArchiveError::VersionTooNew {
+                version,
+                max_supported: ARCHIVE_VERSION,
+            });
+        }
+        Ok(serde_json::from_value(value)?)
+    }
+
+    /// Work out what importing the files of `categories` would change
+    pub fn plan(
+        &self,
+        dirs: &DirectoryContext,
+        categories: &BTreeSet<ArchiveCategory>,
+    ) -> Result<ImportPlan, SettingsArchiveError> {
+        let mut files = Vec::new();
+        for (name, contents) in &self.files {
+            let Some(category) = category_of(name).filter(|c| categories.contains(c)) else {
+                continue;
+            };
+            let target = local_path(dirs, name)
+                .ok_or_else(|| SettingsArchiveError::UnexpectedEntry(name.clone()))?;
+            let change = match std::fs::read(&target) {
+                Ok(current) if current == *contents => FileChange::Unchanged,
+                Ok(_) => FileChange::Replace,
+                Err(_) => FileChange::New,
+            };
+            files.push(PlannedFile {
+                archive_path: name.clone(),
+                category,
+                target,
+                change,
+                contents: contents.clone(),
+            });
+        }
+        Ok(ImportPlan { files })
+    }
+}
+
+/// What importing does to one file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileChange {
+    /// No file there yet
+    New,
+    /// Overwrites a different file, after backing it up
+    Replace,
+    /// Same contents already there; left alone
+    Unchanged,
+}
+
+/// A file an import would write
+#[derive(Debug, Clone)]
+pub struct PlannedFile {
+    pub archive_path: String,
+    pub category: ArchiveCategory,
+    pub target: PathBuf,
+    pub change: FileChange,
+    contents: Vec<u8>,
+}
+
+/// The files an import would write, for previewing and then applying
+#[derive(Debug, Clone, Default)]
+pub struct ImportPlan {
+    pub files: Vec<PlannedFile>,
+}
+
+/// Outcome of [`ImportPlan::apply`]
+#[derive(Debug, Default)]
+pub struct ImportReport {
+    /// Files written, new or replaced
+    pub written: usize,
+    /// Files copied to the backup directory before being replaced
+    pub backed_up: usize,
+}
+
+impl ImportPlan {
+    /// Number of files with `change`
+    pub fn count(&self, change: FileChange) -> usize {
+        self.files.iter().filter(|f| f.change == change).count()
+    }
+
+    /// Whether importing changes anything
+    pub fn is_empty(&self) -> bool {
+        self.files.iter().all(|f| f.change == FileChange::Unchanged)
+    }
+
+    /// Whether a file that only takes effect after a restart changes
+    pub fn needs_restart(&self) -> bool {
+        self.files
+            .iter()
+            .any(|f| f.change != FileChange::Unchanged && f.category.needs_restart())
+    }
+
+    /// Write the files, first copying every file to be replaced into
+    /// `backup_dir` under its archive path
+    ///
+    /// Nothing is written if a backup fails.
+    pub fn apply(&self, backup_dir: &Path) -> io::Result<ImportReport> {
+        let mut report = ImportReport::default();
+        for file in &self.files {
+            if file.change != FileChange::Replace {
+                continue;
+            }
+            let backup = backup_dir.join(&file.archive_path);
+            if let Some(parent) = backup.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::copy(&file.target, &backup)?;
+            report.backed_up += 1;
+        }
+        for file in &self.files {
+            if file.change == FileChange::Unchanged {
+                continue;
+            }
+            if let Some(parent) = file.target.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::write(&file.target, &file.contents)?;
+            report.written += 1;
+        }
+        Ok(report)
+    }
+}
+
+/// A new directory for the files an import replaces
+pub fn backup_dir(dirs: &DirectoryContext) -> PathBuf {
+    let stamp = chrono::Local::now().format("%Y%m%d-%H%M%S").to_string();
+    dirs.settings_backups_dir().join(stamp)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn write(path: &Path, contents: &str) {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, contents).unwrap();
+    }
+
+    /// A machine with a config, a theme, a layout, a session and history
+    fn populated(temp: &TempDir, name: &str) -> DirectoryContext {
+        let dirs = DirectoryContext::for_testing(&temp.path().join(name));
+        write(&dirs.config_path(), r#"{"theme": "dark"}"#);
+        write(&dirs.themes_dir().join("mine.json"), "{}");
+        write(&dirs.sessions_dir().join("layouts/p.json"), "[]");
+        write(&dirs.sessions_dir().join("p.json"), "{}");
+        write(&dirs.search_history_path(), "[\"secret\"]");
+        dirs
+    }
+
+    #[test]
+    fn test_category_of_prefers_the_longest_root() {
+        assert_eq!(
+            category_of("config/config.json"),
+            Some(ArchiveCategory::Config)
+        );
+        assert_eq!(
+            category_of("state/sessions/layouts/a.json"),
+            Some(ArchiveCategory::Layouts)
+        );
+        assert_eq!(
+            category_of("state/sessions/a.json"),
+            Some(ArchiveCategory::Sessions)
+        );
+        assert_eq!(category_of("config/themes-old/a.json"), None);
+        assert_eq!(category_of("state/recovery/a"), None);
+    }
+
+    #[test]
+    fn test_local_path_rejects_escaping_paths() {
+        let temp = TempDir::new().unwrap();
+        let dirs = DirectoryContext::for_testing(temp.path());
+        assert_eq!(
+            local_path(&dirs, "config/themes/a.json"),
+            Some(dirs.themes_dir().join("a.json"))
+        );
+        assert_eq!(local_path(&dirs, "config/themes/../../x"), None);
+        assert_eq!(local_path(&dirs, "config//x"), None);
+        assert_eq!(local_path(&dirs, "/etc/passwd"), None);
+    }
+
+    #[test]
+    fn test_round_trip_with_default_categories() {
+        let temp = TempDir::new().unwrap();
+        let old = populated(&temp, "old");
+        let archive = temp.path().join("settings.tar.gz");
+        let manifest = export(&old, &ArchiveCategory::defaults(), &archive).unwrap();
+        assert_eq!(
+            manifest.files,
+            vec![
+                "config/config.json",
+                "config/themes/mine.json",
+                "state/sessions/layouts/p.json"
+            ]
+        );
+
+        let new = DirectoryContext::for_testing(&temp.path().join("new"));
+        let archive = SettingsArchive::read(&archive).unwrap();
+        assert_eq!(archive.manifest, manifest);
+        let plan = archive.plan(&new, &ArchiveCategory::defaults()).unwrap();
+        assert_eq!(plan.count(FileChange::New), 3);
+        assert!(plan.needs_restart());
+        let report = plan.apply(&temp.path().join("backup")).unwrap();
+        assert_eq!((report.written, report.backed_up), (3, 0));
+
+        assert_eq!(
+            std::fs::read_to_string(new.themes_dir().join("mine.json")).unwrap(),
+            "{}"
+        );
+        assert!(!new.search_history_path().exists());
+        assert!(!new.sessions_dir().join("p.json").exists());
+    }
+
+    #[test]
+    fn test_opt_in_categories_are_exported_when_chosen() {
+        let temp = TempDir::new().unwrap();
+        let old = populated(&temp, "old");
+        let archive = temp.path().join("settings.tar.gz");
+        let categories = ArchiveCategory::parse_list("sessions, history").unwrap();
+        let manifest = export(&old, &categories, &archive).unwrap();
+        assert_eq!(
+            manifest.files,
+            vec!["state/sessions/p.json", "state/search_history.json"]
+        );
+        assert!(matches!(
+            ArchiveCategory::parse_list("config,bogus"),
+            Err(SettingsArchiveError::UnknownCategory(name)) if name == "bogus"
+        ));
+    }
+
+    #[test]
+    fn test_import_backs_up_replaced_files_first() {
+        let temp = TempDir::new().unwrap();
+        let old = populated(&temp, "old");
+        let archive = temp.path().join("settings.tar.gz");
+        export(&old, &ArchiveCategory::defaults(), &archive).unwrap();
+
+        let new = DirectoryContext::for_testing(&temp.path().join("new"));
+        write(&new.config_path(), r#"{"theme": "light"}"#);
+        write(&new.themes_dir().join("mine.json"), "{}");
+
+        let plan = SettingsArchive::read(&archive)
+            .unwrap()
+            .plan(&new, &ArchiveCategory::defaults())
+            .unwrap();
+        assert_eq!(plan.count(FileChange::Replace), 1);
+        assert_eq!(plan.count(FileChange::Unchanged), 1);
+        assert_eq!(plan.count(FileChange::New), 1);
+
+        let backup = temp.path().join("backup");
+        let report = plan.apply(&backup).unwrap();
+        assert_eq!((report.written, report.backed_up), (2, 1));
+        assert_eq!(
+            std::fs::read_to_string(backup.join("config/config.json")).unwrap(),
+            r#"{"theme": "light"}"#
+        );
+        assert_eq!(
+            std::fs::read_to_string(new.config_path()).unwrap(),
+            r#"{"theme": "dark"}"#
+        );
+    }
+
+    #[test]
+    fn test_newer_and_foreign_archives_are_refused() {
+        let temp = TempDir::new().unwrap();
+        let write_archive = |name: &str, entries: &[(&str, &str)]| {
+            let path = temp.path().join(name);
+            let file = std::fs::File::create(&path).unwrap();
+            let mut builder = tar::Builder::new(GzEncoder::new(file, Compression::default()));
+            for (entry, data) in entries {
+                let mut header = tar::Header::new_gnu();
+                header.set_size(data.len() as u64);
+                header.set_mode(0o644);
+                header.set_cksum();
+                builder
+                    .append_data(&mut header, entry, data.as_bytes())
+                    .unwrap();
+            }
+            builder.into_inner().unwrap().finish().unwrap();
+            path
+        };
+
+        let newer = write_archive(
+            "newer.tar.gz",
+            &[(
+                MANIFEST_NAME,
+                r#"{"format": "fresh-settings", "version": 99}"#,
+            )],
+        );
+        assert!(matches!(
+            SettingsArchive::read(&newer),
+            Err(SettingsArchiveError::VersionTooNew { version: 99, .. })
+        ));
+
+        let foreign = write_archive("foreign.tar.gz", &[("README", "hello")]);
+        assert!(matches!(
+            SettingsArchive::read(&foreign),
+            Err(SettingsArchiveError::NotAnArchive)
+        ));
+
+        let manifest = r#"{"format": "fresh-settings", "version": 1, "fresh_version": "0",
+            "categories": [], "files": []}"#;
+        let stray = write_archive(
+            "stray.tar.gz",
+            &[(MANIFEST_NAME, manifest), ("state/recovery/x", "")],
+        );
+        assert!(matches!(
+            SettingsArchive::read(&stray),
+            Err(SettingsArchiveError::UnexpectedEntry(path)) if path == "state/recovery/x"
+        ));
+    }
+}
@@ -0,0 +1,327 @@
+//! Startup profiling for `--profile-startup`
+//!
+//! Initialization code wraps each phase in [`phase`] (or [`phase_with`] when
+//! the name has to be built), which records its wall time while the guard is
+//! alive, and [`count`] attaches cheap counts to the running phase. Phases
+//! opened while another one is running are nested under it, so plugin loading
+//! lists each plugin and session restore lists each buffer.
+//! Plugins load on the plugin thread while the main thread waits for them,
+//! which is why nesting follows a single global stack rather than one per
+//! thread.
+//!
+//! Once the first frame is drawn, [`complete`] stops recording, logs every
+//! phase as a tracing event and writes the report to the requested file, or
+//! keeps it for [`take_exit_report`] to print on stderr after the terminal is
+//! restored.
+//!
+//! Until [`enable`] is called, [`phase`] is a single relaxed atomic load.
+
+use std::fmt;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Phases taking longer than this are flagged in the report
+pub const SLOW_PHASE_THRESHOLD: Duration = Duration::from_millis(100);
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static PROFILE: Mutex<Option<Profile>> = Mutex::new(None);
+static EXIT_REPORT: Mutex<Option<StartupReport>> = Mutex::new(None);
+
+/// One timed phase of the startup
+#[derive(Debug, Clone, PartialEq)]
+pub struct PhaseRecord {
+    pub name: String,
+    /// Nesting level, 0 for top-level phases
+    pub depth: usize,
+    /// Wall time, None if the phase was still running when recording stopped
+    pub duration: Option<Duration>,
+    /// Cheap counts attached to the phase (buffers restored, plugins loaded, ...)
+    pub counts: Vec<(&'static str, usize)>,
+}
+
+impl PhaseRecord {
+    fn is_slow(&self) -> bool {
+        self.duration.is_some_and(|d| d > SLOW_PHASE_THRESHOLD)
+    }
+}
+
+/// Phases recorded so far
+#[derive(Debug)]
+struct Profile {
+    started: Instant,
+    output: Option<PathBuf>,
+    phases: Vec<PhaseRecord>,
+    /// Indices of the phases still running, innermost last
+    open: Vec<usize>,
+}
+
+impl Profile {
+    fn new(output: Option<PathBuf>) -> Self {
+        Self {
+            started: Instant::now(),
+            output,
+            phases: Vec::new(),
+            open: Vec::new(),
+        }
+    }
+
+    fn begin(&mut self, name: String) -> usize {
+        let index = self.phases.len();
+        self.phases.push(PhaseRecord {
+            name,
+            depth: self.open.len(),
+            duration: None,
+            counts: Vec::new(),
+        });
+        self.open.push(index);
+        index
+    }
+
+    fn end(&mut self, index: usize, duration: Duration) {
+        if let Some(record) = self.phases.get_mut(index) {
+            record.duration = Some(duration);
+        }
+        self.open.retain(|&open| open != index);
+    }
+
+    /// Attach a count to the innermost running phase
+    fn count(&mut self, label: &'static str, value: usize) {
+        if let Some(record) = self.open.last().and_then(|&i| self.phases.get_mut(i)) {
+            record.counts.push((label, value));
+        }
+    }
+
+    fn report(self) -> StartupReport {
+        StartupReport {
+            total: self.started.elapsed(),
+            phases: self.phases,
+        }
+    }
+}
+
+/// A running phase, timed until dropped
+#[must_use = "the phase ends when the guard is dropped"]
+pub struct Phase {
+    index: usize,
+    started: Instant,
+}
+
+impl Drop for Phase {
+    fn drop(&mut self) {
+        if let Some(profile) = PROFILE.lock().unwrap().as_mut() {
+            profile.end(self.index, self.started.elapsed());
+        }
+    }
+}
+
+/// Start recording; the report goes to `output`, or to stderr on exit if None
+pub fn enable(output: Option<PathBuf>) {
+    *PROFILE.lock().unwrap() = Some(Profile::new(output));
+    ENABLED.store(true, Ordering::Relaxed);
+}
+
+/// Whether phases are being recorded
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// Time a phase until the returned guard is dropped
+pub fn phase(name: &'static str) -> Option<Phase> {
+    phase_with(|| name.to_string())
+}
+
+/// Like [`phase`], for names that are only built while profiling
+pub fn phase_with(name: impl FnOnce() -> String) -> Option<Phase> {
+    if !is_enabled() {
+        return None;
+    }
+    let index = PROFILE.lock().unwrap().as_mut()?.begin(name());
+    Some(Phase {
+        index,
+        started: Instant::now(),
+    })
+}
+
+/// Attach a count to the innermost running phase
+pub fn count(label: &'static str, value: usize) {
+    if !is_enabled() {
+        return;
+    }
+    if let Some(profile) = PROFILE.lock().unwrap().as_mut() {
+        profile.count(label, value);
+    }
+}
+
+/// Stop recording, without logging or writing anything
+pub fn finish() -> Option<StartupReport> {
+    ENABLED.store(false, Ordering::Relaxed);
+    PROFILE.lock().unwrap().take().map(Profile::report)
+}
+
+/// Stop recording after the first frame and deliver the report
+///
+/// Every phase is logged as a tracing event. The report is written to the
+/// file given to [`enable`], or kept for [`take_exit_report`].
+pub fn complete() {
+    ENABLED.store(false, Ordering::Relaxed);
+    let Some(profile) = PROFILE.lock().unwrap().take() else {
+        return;
+    };
+    let output = profile.output.clone();
+    let report = profile.report();
+    report.log();
+
+    match output {
+        Some(path) => {
+            if let Err(e) = std::fs::write(&path, report.to_string()) {
+                tracing::warn!(
+                    "Failed to write startup profile to {}: {}",
+                    path.display(),
+                    e
+                );
+            }
+        }
+        None => *EXIT_REPORT.lock().unwrap() = Some(report),
+    }
+}
+
+/// The report to print on stderr once the terminal is restored
+pub fn take_exit_report() -> Option<StartupReport> {
+    EXIT_REPORT.lock().unwrap().take()
+}
+
+/// Breakdown of the startup time by phase
+#[derive(Debug, Clone)]
+pub struct StartupReport {
+    /// Time from enabling the profile to completing it
+    pub total: Duration,
+    /// Phases in the order they started; children follow their parent
+    pub phases: Vec<PhaseRecord>,
+}
+
+impl StartupReport {
+    /// Whether a phase with this name was recorded
+    pub fn has_phase(&self, name: &str) -> bool {
+        self.phases.iter().any(|p| p.name == name)
+    }
+
+    /// Write each phase to the tracing log
+    fn log(&self) {
+        for phase in &self.phases {
+            tracing::info!(
+                target: "startup_profile",
+                phase = %phase.name,
+                depth = phase.depth,
+                ms = phase.duration.map(|d| d.as_secs_f64() * 1000.0),
+                slow = phase.is_slow(),
+                counts = ?phase.counts,
+                "startup phase"
+            );
+        }
+        tracing::info!(
+            target: "startup_profile",
+            total_ms = self.total.as_secs_f64() * 1000.0,
+            "startup complete"
+        );
+    }
+}
+
+impl fmt::Display for StartupReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "Startup profile: {:.1} ms to first frame (phases over {} ms marked SLOW)",
+            self.total.as_secs_f64() * 1000.0,
+            SLOW_PHASE_THRESHOLD.as_millis()
+        )?;
+        let name_width = self
+            .phases
+            .iter()
+            .map(|p| p.depth * 2 + p.name.chars().count())
+            .max()
+            .unwrap_or(0);
+        for phase in &self.phases {
+            let label = format!("{}{}", "  ".repeat(phase.depth), phase.name);
+            let time = match phase.duration {
+                Some(d) => format!("{:>9.1} ms", d.as_secs_f64() * 1000.0),
+                None => format!("{:>12}", "unfinished"),
+            };
+            let mut line = format!("  {:<width$}  {}", label, time, width = name_width);
+            if phase.is_slow() {
+                line.push_str("  SLOW");
+            }
+            for (label, value) in &phase.counts {
+                line.push_str(&format!("  {}={}", label, value));
+            }
+            writeln!(f, "{}", line)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_phases_nest_under_the_running_phase() {
+        let mut profile = Profile::new(None);
+        let plugins = profile.begin("plugins".to_string());
+        let first = profile.begin("plugin a".to_string());
+        profile.end(first, Duration::from_millis(5));
+        let second = profile.begin("plugin b".to_string());
+        profile.end(second, Duration::from_millis(7));
+        profile.count("loaded", 2);
+        profile.end(plugins, Duration::from_millis(12));
+        let frame = profile.begin("first_frame".to_string());
+
+        let report = profile.report();
+        let depths: Vec<_> = report
+            .phases
+            .iter()
+            .map(|p| (p.name.as_str(), p.depth))
+            .collect();
+        assert_eq!(
+            depths,
+            vec![
+                ("plugins", 0),
+                ("plugin a", 1),
+                ("plugin b", 1),
+                ("first_frame", 0)
+            ]
+        );
+        assert_eq!(report.phases[0].counts, vec![("loaded", 2)]);
+        assert_eq!(report.phases[frame].duration, None);
+    }
+
+    #[test]
+    fn test_report_flags_slow_phases() {
+        let report = StartupReport {
+            total: Duration::from_millis(400),
+            phases: vec![
+                PhaseRecord {
+                    name: "editor".to_string(),
+                    depth: 0,
+                    duration: Some(Duration::from_millis(350)),
+                    counts: Vec::new(),
+                },
+                PhaseRecord {
+                    name: "session_restore".to_string(),
+                    depth: 1,
+                    duration: Some(Duration::from_millis(3)),
+                    counts: vec![("buffers", 4)],
+                },
+            ],
+        };
+        let text = report.to_string();
+        let lines: Vec<_> = text.lines().collect();
+        assert!(lines[0].contains("400.0 ms"));
+        assert!(lines[1].trim_start().starts_with("editor"));
+        assert!(lines[1].ends_with("SLOW"));
+        assert!(lines[2].starts_with("    session_restore"));
+        assert!(!lines[2].contains("SLOW"));
+        assert!(lines[2].ends_with("buffers=4"));
+    }
+}
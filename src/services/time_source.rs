@@ -7,7 +7,7 @@
 //! See `docs/internal/TIMESOURCE_DESIGN.md` for the full design document.
 
 use std::sync::atomic::{AtomicU64, Ordering};
-use std::sync::Arc;
+use std::sync::{Arc, OnceLock};
 use std::time::{Duration, Instant};
 
 /// Abstraction over time-related operations.
@@ -144,6 +144,68 @@ impl TimeSource for TestTimeSource {
     }
 }
 
+/// Virtual time while an event log is replayed, real time afterwards.
+///
+/// - Until [`ReplayTimeSource::go_live`], behaves like [`TestTimeSource`]:
+///   time only moves when advanced
+/// - Afterwards, time runs at real speed from where replay left it, so the
+///   editor can be used as normal
+#[derive(Debug)]
+pub struct ReplayTimeSource {
+    /// Virtual time in nanoseconds, as advanced during replay.
+    logical_nanos: AtomicU64,
+    /// Base instant (real time at creation, used for Instant arithmetic).
+    base_instant: Instant,
+    /// Real time at which replay ended.
+    live_since: OnceLock<Instant>,
+}
+
+impl Default for ReplayTimeSource {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ReplayTimeSource {
+    /// Create a new ReplayTimeSource with virtual time starting at zero.
+    pub fn new() -> Self {
+        Self {
+            logical_nanos: AtomicU64::new(0),
+            base_instant: Instant::now(),
+            live_since: OnceLock::new(),
+        }
+    }
+
+    /// Advance virtual time by the given duration; no effect once live.
+    pub fn advance(&self, duration: Duration) {
+        if self.live_since.get().is_none() {
+            self.logical_nanos
+                .fetch_add(duration.as_nanos() as u64, Ordering::SeqCst);
+        }
+    }
+
+    /// Let time follow the real clock from now on.
+    pub fn go_live(&self) {
+        let _ = self.live_since.set(Instant::now());
+    }
+}
+
+impl TimeSource for ReplayTimeSource {
+    fn now(&self) -> Instant {
+        let logical = Duration::from_nanos(self.logical_nanos.load(Ordering::SeqCst));
+        let real = self.live_since.get().map_or(Duration::ZERO, Instant::elapsed);
+        self.base_instant + logical + real
+    }
+
+    fn sleep(&self, duration: Duration) {
+        if self.live_since.get().is_some() {
+            std::thread::sleep(duration);
+        } else {
+            self.advance(duration);
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -218,6 +280,22 @@ mod tests {
         assert_eq!(ts.elapsed(), Duration::from_millis(200));
     }
 
+    #[test]
+    fn replay_time_source_runs_in_real_time_once_live() {
+        let ts = ReplayTimeSource::new();
+        let start = ts.now();
+
+        ts.sleep(Duration::from_secs(60));
+        assert_eq!(ts.elapsed_since(start), Duration::from_secs(60));
+
+        ts.go_live();
+        ts.advance(Duration::from_secs(60));
+        std::thread::sleep(Duration::from_millis(1));
+        let elapsed = ts.elapsed_since(start);
+        assert!(elapsed > Duration::from_secs(60));
+        assert!(elapsed < Duration::from_secs(120));
+    }
+
     #[test]
     fn shared_time_source_works() {
         let real: SharedTimeSource = RealTimeSource::shared();
@@ -0,0 +1,421 @@
+//! Finding TODO/FIXME-style comments
+//!
+//! [`TodoMatcher`] finds the configured keywords in text. Matches are only
+//! reported inside comments, which are located with the same highlighting
+//! engine the editor renders with, so a `"TODO"` string literal is ignored.
+//! Text without a syntax (plain text, unknown extensions) has no comment
+//! information and is matched everywhere.
+//!
+//! [`start_scan`] runs a project-wide scan on the worker pool: it walks the
+//! working directory with the `ignore` crate, so hidden and `.gitignore`d
+//! files are skipped, and reports every match in an
+//! [`AsyncMessage::TodoScanFinished`].
+
+use crate::config::{LanguageConfig, TodoKeyword};
+use crate::model::buffer::Buffer;
+use crate::primitives::grammar_registry::GrammarRegistry;
+use crate::primitives::highlight_engine::HighlightEngine;
+use crate::primitives::highlighter::{HighlightCategory, HighlightSpan};
+use crate::services::async_bridge::AsyncMessage;
+use crate::services::worker_pool::{JobHandle, JobPriority, WorkerPool};
+use crate::view::theme::Theme;
+use std::collections::{HashMap, HashSet};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Files larger than this are not scanned (the highlighter doesn't parse them either)
+pub const MAX_SCAN_FILE_BYTES: u64 = 1024 * 1024;
+
+/// Bytes inspected to decide whether a file is binary
+const BINARY_SNIFF_BYTES: usize = 8 * 1024;
+
+/// A keyword occurrence within one line
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeywordMatch {
+    /// Index of the keyword in the matcher's keyword list
+    pub keyword: usize,
+    /// Byte range of the keyword, relative to the start of the line
+    pub range: Range<usize>,
+    /// Name in parentheses right after the keyword, as in `TODO(alice)`
+    pub author: Option<String>,
+    /// Rest of the comment after the keyword
+    pub text: String,
+}
+
+/// A keyword occurrence found by a project scan
+#[derive(Debug, Clone, PartialEq)]
+pub struct TodoItem {
+    pub path: PathBuf,
+    /// 1-based line number
+    pub line: usize,
+    /// 1-based byte column of the keyword
+    pub column: usize,
+    /// The keyword as configured (e.g. "FIXME")
+    pub keyword: String,
+    pub author: Option<String>,
+    pub text: String,
+}
+
+/// Finds configured keywords as whole words
+#[derive(Debug, Clone)]
+pub struct TodoMatcher {
+    keywords: Vec<String>,
+}
+
+impl TodoMatcher {
+    pub fn new(keywords: &[TodoKeyword]) -> Self {
+        Self {
+            keywords: keywords.iter().map(|k| k.word.clone()).collect(),
+        }
+    }
+
+    /// Configured keywords, in config order (match indices refer to this)
+    pub fn keywords(&self) -> &[String] {
+        &self.keywords
+    }
+
+    /// Find all keyword occurrences in one line, in order of position
+    pub fn match_line(&self, line: &str) -> Vec<KeywordMatch> {
+        let line = line.trim_end_matches(['\n', '\r']);
+        let mut matches = Vec::new();
+        for (keyword, word) in self.keywords.iter().enumerate() {
+            if word.is_empty() {
+                continue;
+            }
+            for (start, _) in line.match_indices(word.as_str()) {
+                let end = start + word.len();
+                let before = line[..start].chars().next_back();
+                let after = line[end..].chars().next();
+                if before.is_some_and(is_word_char) || after.is_some_and(is_word_char) {
+                    continue;
+                }
+                let (author, rest) = split_author(&line[end..]);
+                matches.push(KeywordMatch {
+                    keyword,
+                    range: start..end,
+                    author,
+                    text: comment_text(rest),
+                });
+            }
+        }
+        matches.sort_by_key(|m| m.range.start);
+        matches
+    }
+
+    /// Find keyword occurrences in a whole text
+    ///
+    /// With `comments`, only keywords lying inside one of the (sorted) comment
+    /// ranges are reported; `None` means there is no syntax information and
+    /// every occurrence counts.
+    pub fn scan(
+        &self,
+        path: &Path,
+        text: &str,
+        comments: Option<&[Range<usize>]>,
+    ) -> Vec<TodoItem> {
+        let mut items = Vec::new();
+        let mut line_start = 0;
+        for (index, line) in text.split_inclusive('\n').enumerate() {
+            for found in self.match_line(line) {
+                let range = line_start + found.range.start..line_start + found.range.end;
+                if comments.is_some_and(|comments| !in_comment(comments, &range)) {
+                    continue;
+                }
+                items.push(TodoItem {
+                    path: path.to_path_buf(),
+                    line: index + 1,
+                    column: found.range.start + 1,
+                    keyword: self.keywords[found.keyword].clone(),
+                    author: found.author,
+                    text: found.text,
+                });
+            }
+            line_start += line.len();
+        }
+        items
+    }
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+/// Split `(author)` off the text following a keyword
+fn split_author(rest: &str) -> (Option<String>, &str) {
+    let Some(inner) = rest.strip_prefix('(') else {
+        return (None, rest);
+    };
+    match inner.find(')') {
+        Some(close) if !inner[..close].trim().is_empty() => {
+            (Some(inner[..close].trim().to_string()), &inner[close + 1..])
+        }
+        _ => (None, rest),
+    }
+}
+
+/// The comment text after a keyword, without separators and comment closers
+fn comment_text(rest: &str) -> String {
+    let text = rest.trim_start_matches([':', '-', ' ', '\t']).trim_end();
+    let text = text
+        .strip_suffix("*/")
+        .or_else(|| text.strip_suffix("-->"))
+        .unwrap_or(text);
+    text.trim_end().to_string()
+}
+
+/// Whether `range` lies inside the comment ranges (adjacent ranges count as one)
+pub fn in_comment(comments: &[Range<usize>], range: &Range<usize>) -> bool {
+    let covers = |offset: usize| {
+        comments
+            .iter()
+            .any(|comment| comment.start <= offset && offset < comment.end)
+    };
+    !range.is_empty() && covers(range.start) && covers(range.end - 1)
+}
+
+/// Byte ranges of comment spans, sorted by start
+pub fn comment_ranges(spans: &[HighlightSpan]) -> Vec<Range<usize>> {
+    let mut ranges: Vec<Range<usize>> = spans
+        .iter()
+        .filter(|span| span.category == Some(HighlightCategory::Comment))
+        .map(|span| span.range.clone())
+        .collect();
+    ranges.sort_by_key(|range| range.start);
+    ranges
+}
+
+/// Whether the engine's syntax can tell comments apart from other text
+pub fn knows_comments(engine: &HighlightEngine) -> bool {
+    engine.has_highlighting() && engine.syntax_name() != Some("Plain Text")
+}
+
+/// Everything a scan job needs, captured on the main thread
+pub struct TodoScanRequest {
+    /// Identifies the scan in the result message
+    pub scan_id: u64,
+    pub root: PathBuf,
+    pub matcher: TodoMatcher,
+    /// Current text of open buffers, used instead of the file on disk so
+    /// unsaved edits are included
+    pub open_buffers: HashMap<PathBuf, String>,
+    pub grammar_registry: Arc<GrammarRegistry>,
+    pub languages: HashMap<String, LanguageConfig>,
+    pub theme: Theme,
+}
+
+/// Scan `request.root` on the worker pool
+///
+/// The result arrives as [`AsyncMessage::TodoScanFinished`] (nothing is sent
+/// if the job is cancelled).
+pub fn start_scan(pool: &WorkerPool, request: TodoScanRequest) -> JobHandle {
+    pool.submit("todo-scan", JobPriority::Normal, move |token| {
+        let mut items = Vec::new();
+        let mut seen = HashSet::new();
+        let walker = ignore::WalkBuilder::new(&request.root)
+            .require_git(false)
+            .build();
+        for entry in walker.flatten() {
+            if token.is_cancelled() {
+                return None;
+            }
+            if !entry.file_type().is_some_and(|t| t.is_file()) {
+                continue;
+            }
+            let path = entry.path();
+            seen.insert(path.to_path_buf());
+            if let Some(text) = request.text_of(path) {
+                items.extend(request.scan_text(path, &text));
+            }
+        }
+
+        // Open buffers whose file isn't on disk (yet) or was skipped by the walk
+        for (path, text) in &request.open_buffers {
+            if !seen.contains(path) && path.starts_with(&request.root) {
+                items.extend(request.scan_text(path, text));
+            }
+        }
+
+        items.sort_by(|a, b| a.path.cmp(&b.path).then(a.line.cmp(&b.line)));
+        Some(AsyncMessage::TodoScanFinished {
+            scan_id: request.scan_id,
+            items,
+        })
+    })
+}
+
+impl TodoScanRequest {
+    /// Text to scan for `path`: the open buffer's, or the file's if it is small text
+    fn text_of(&self, path: &Path) -> Option<String> {
+        if let Some(text) = self.open_buffers.get(path) {
+            return Some(text.clone());
+        }
+        if std::fs::metadata(path).ok()?.len() > MAX_SCAN_FILE_BYTES {
+            return None;
+        }
+        let bytes = std::fs::read(path).ok()?;
+        if Buffer::detect_binary(&bytes[..bytes.len().min(BINARY_SNIFF_BYTES)]) {
+            return None;
+        }
+        String::from_utf8(bytes).ok()
+    }
+
+    /// Find the matches in one file, using its syntax to locate comments
+    pub fn scan_text(&self, path: &Path, text: &str) -> Vec<TodoItem> {
+        // Cheap check first: most files have no keyword at all
+        if !self
+            .matcher
+            .keywords()
+            .iter()
+            .any(|word| !word.is_empty() && text.contains(word.as_str()))
+        {
+            return Vec::new();
+        }
+
+        let mut engine =
+            HighlightEngine::for_file_with_languages(path, &self.grammar_registry, &self.languages);
+        if !knows_comments(&engine) {
+            return self.matcher.scan(path, text, None);
+        }
+        let buffer = Buffer::from_str(text, 0);
+        let spans = engine.highlight_viewport(&buffer, 0, text.len(), &self.theme, 0);
+        self.matcher.scan(path, text, Some(&comment_ranges(&spans)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matcher() -> TodoMatcher {
+        TodoMatcher::new(&crate::config::TodosConfig::default().keywords)
+    }
+
+    fn request(root: &Path) -> TodoScanRequest {
+        TodoScanRequest {
+            scan_id: 1,
+            root: root.to_path_buf(),
+            matcher: matcher(),
+            open_buffers: HashMap::new(),
+            grammar_registry: Arc::new(GrammarRegistry::load()),
+            languages: crate::config::Config::default().languages,
+            theme: Theme::dark(),
+        }
+    }
+
+    #[test]
+    fn test_match_line_whole_words_and_author() {
+        let matches = matcher().match_line("// TODO(alice): rename this, not a TODOS list\n");
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].range, 3..7);
+        assert_eq!(matches[0].author.as_deref(), Some("alice"));
+        assert_eq!(matches[0].text, "rename this, not a TODOS list");
+
+        let matches = matcher().match_line("/* FIXME: leaks */ x = 1; // HACK");
+        assert_eq!(matches.len(), 2);
+        assert_eq!(matches[0].text, "leaks */ x = 1; // HACK");
+        assert_eq!(matches[1].keyword, 2);
+        assert_eq!(matches[1].author, None);
+    }
+
+    #[test]
+    fn test_comment_text_strips_closers() {
+        let matches = matcher().match_line("<!-- XXX: check the layout -->");
+        assert_eq!(matches[0].text, "check the layout");
+        let matches = matcher().match_line("/* TODO() nothing */");
+        assert_eq!(matches[0].author, None);
+        assert_eq!(matches[0].text, "() nothing");
+    }
+
+    #[test]
+    fn test_keywords_outside_comments_are_skipped() {
+        let text = "let a = \"TODO\";\n// TODO: real one\n";
+        let comments = [16..34];
+        let items = matcher().scan(Path::new("a.rs"), text, Some(&comments));
+        assert_eq!(items.len(), 1);
+        assert_eq!((items[0].line, items[0].column), (2, 4));
+        assert_eq!(items[0].text, "real one");
+
+        let items = matcher().scan(Path::new("a.txt"), text, None);
+        assert_eq!(items.len(), 2);
+    }
+
+    #[test]
+    fn test_scan_text_uses_syntax_for_comments() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let request = request(temp_dir.path());
+        let rust = concat!(
+            "fn main() {\n",
+            "    let s = \"TODO: not a comment\";\n",
+            "    // FIXME(bob): handle errors\n",
+            "    /* HACK */ let x = 'X'; // XXX\n",
+            "}\n",
+        );
+        let items = request.scan_text(Path::new("src/main.rs"), rust);
+        let found: Vec<_> = items
+            .iter()
+            .map(|item| (item.line, item.keyword.as_str(), item.author.as_deref()))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                (3, "FIXME", Some("bob")),
+                (4, "HACK", None),
+                (4, "XXX", None)
+            ]
+        );
+
+        let python = "s = 'HACK: string'\n# TODO: comment\n";
+        let items = request.scan_text(Path::new("tool.py"), python);
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].keyword, "TODO");
+    }
+
+    #[test]
+    fn test_scan_walks_project_and_prefers_open_buffers() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let root = temp_dir.path();
+        std::fs::create_dir(root.join("src")).unwrap();
+        std::fs::write(root.join("src/lib.rs"), "// TODO: on disk\n").unwrap();
+        std::fs::write(root.join("notes.txt"), "FIXME everywhere\n").unwrap();
+        std::fs::write(root.join(".gitignore"), "ignored/\n").unwrap();
+        std::fs::create_dir(root.join("ignored")).unwrap();
+        std::fs::write(root.join("ignored/skip.rs"), "// TODO: ignored\n").unwrap();
+        std::fs::write(root.join("blob.bin"), b"TODO\0\0\0").unwrap();
+
+        let (sender, receiver) = std::sync::mpsc::channel();
+        let pool = WorkerPool::new(1, Some(sender));
+        let mut request = request(root);
+        request
+            .open_buffers
+            .insert(root.join("src/lib.rs"), "// HACK: unsaved\n".to_string());
+        request.open_buffers.insert(
+            root.join("src/new.rs"),
+            "// XXX: not saved yet\n".to_string(),
+        );
+        start_scan(&pool, request);
+
+        let message = receiver
+            .recv_timeout(std::time::Duration::from_secs(30))
+            .unwrap();
+        let AsyncMessage::TodoScanFinished { scan_id, items } = message else {
+            panic!("unexpected message");
+        };
+        assert_eq!(scan_id, 1);
+        let found: Vec<_> = items
+            .iter()
+            .map(|item| {
+                let path = item.path.strip_prefix(root).unwrap();
+                (path.to_string_lossy().into_owned(), item.keyword.clone())
+            })
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                ("notes.txt".to_string(), "FIXME".to_string()),
+                ("src/lib.rs".to_string(), "HACK".to_string()),
+                ("src/new.rs".to_string(), "XXX".to_string()),
+            ]
+        );
+    }
+}
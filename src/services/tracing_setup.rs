@@ -3,46 +3,67 @@
 //! This module provides shared tracing configuration used by both
 //! the main application and tests.
 
-use std::fs::File;
 use std::path::Path;
-use std::sync::Arc;
+use std::sync::OnceLock;
+use tracing_subscriber::fmt::MakeWriter;
 use tracing_subscriber::prelude::*;
 use tracing_subscriber::{fmt, EnvFilter};
 
+use super::rotating_log::{LogLimits, RotatingLog};
 use super::warning_log::{WarningLogHandle, WarningLogLayer};
 
+/// The log set up by [`init_global`]
+static GLOBAL_LOG: OnceLock<RotatingLog> = OnceLock::new();
+
 /// Initialize the global tracing subscriber with file logging and warning capture.
 ///
 /// This sets up:
-/// - File-based logging with the given log file
+/// - Logging to the given file, rotated at a size limit by a background
+///   writer (see [`RotatingLog`] and [`global_log`])
 /// - Environment-based filtering (RUST_LOG) with DEBUG default
 /// - Warning log layer that captures WARN+ to a separate file
 ///
 /// Returns the warning log handle if successful, None if setup failed.
 pub fn init_global(log_file_path: &Path) -> Option<WarningLogHandle> {
     let (warning_layer, warning_handle) = super::warning_log::create().ok()?;
-    let log_file = File::create(log_file_path).ok()?;
+    let log = RotatingLog::create(log_file_path, LogLimits::default()).ok()?;
 
-    let subscriber = build_subscriber(log_file, Some(warning_layer));
+    let subscriber = build_subscriber(log.clone(), Some(warning_layer));
     subscriber.init();
+    let _ = GLOBAL_LOG.set(log);
 
     Some(warning_handle)
 }
 
+/// The log file written by the global subscriber, if [`init_global`] set one up
+pub fn global_log() -> Option<&'static RotatingLog> {
+    GLOBAL_LOG.get()
+}
+
+/// Write out the events still queued for the global log, before exiting
+pub fn flush_global() {
+    if let Some(log) = GLOBAL_LOG.get() {
+        log.flush();
+    }
+}
+
 /// Build a subscriber with file logging and optional warning layer.
 ///
 /// This is the core subscriber configuration shared between production and tests.
-pub fn build_subscriber(
-    log_file: File,
+pub fn build_subscriber<W>(
+    log_writer: W,
     warning_layer: Option<WarningLogLayer>,
-) -> impl tracing::Subscriber + Send + Sync {
+) -> impl tracing::Subscriber + Send + Sync
+where
+    W: for<'a> MakeWriter<'a> + Send + Sync + 'static,
+{
     let env_filter = EnvFilter::from_default_env()
         .add_directive(tracing::Level::DEBUG.into())
         // Suppress noisy SWC debug logs
         .add_directive("swc_ecma_transforms_base=info".parse().unwrap())
         .add_directive("swc_common=info".parse().unwrap());
 
-    let fmt_layer = fmt::layer().with_writer(Arc::new(log_file));
+    let fmt_layer = fmt::layer().with_writer(log_writer);
 
     tracing_subscriber::registry()
         .with(fmt_layer)
@@ -53,6 +74,7 @@ pub fn build_subscriber(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::sync::Arc;
     use std::time::Duration;
     use tempfile::{NamedTempFile, TempPath};
 
@@ -72,7 +94,8 @@ mod tests {
         let (warning_layer, warning_handle) =
             super::super::warning_log::create_with_path(warning_log_path.to_path_buf()).unwrap();
 
-        let subscriber = build_subscriber(log_file.reopen().unwrap(), Some(warning_layer));
+        let subscriber =
+            build_subscriber(Arc::new(log_file.reopen().unwrap()), Some(warning_layer));
 
         TestSubscriber {
             subscriber: Box::new(subscriber),
@@ -0,0 +1,335 @@
+//! Local usage statistics
+//!
+//! With `stats.enabled` on, the editor counts the commands it runs, the keys
+//! pressed, the time the terminal had focus and the files opened, grouped
+//! per local day. The counts are kept in `usage_stats.json` in the state
+//! directory and never leave the machine. Days older than the retention
+//! window are dropped whenever the file is written.
+
+use std::collections::BTreeMap;
+use std::io;
+use std::path::Path;
+
+use chrono::{Days, NaiveDate};
+use serde::{Deserialize, Serialize};
+
+/// Format of the day keys, which sort in date order
+const DAY_FORMAT: &str = "%Y-%m-%d";
+
+/// Levels of the daily activity sparkline, lowest first
+const SPARK_LEVELS: &[u8] = b"_.-=+*#@";
+
+/// Counts for one day
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DayStats {
+    /// Times each command ran, by action name
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub commands: BTreeMap<String, u64>,
+    /// Keys pressed
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub keys: u64,
+    /// Seconds the terminal had focus
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub focused_secs: u64,
+    /// Times each file was opened, by path
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub files: BTreeMap<String, u64>,
+    /// Files opened per language
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub languages: BTreeMap<String, u64>,
+}
+
+fn is_zero(n: &u64) -> bool {
+    *n == 0
+}
+
+impl DayStats {
+    /// Whether nothing was counted
+    pub fn is_empty(&self) -> bool {
+        self == &Self::default()
+    }
+
+    /// Add the counts of `other`
+    pub fn merge(&mut self, other: &DayStats) {
+        add_counts(&mut self.commands, &other.commands);
+        self.keys += other.keys;
+        self.focused_secs += other.focused_secs;
+        add_counts(&mut self.files, &other.files);
+        add_counts(&mut self.languages, &other.languages);
+    }
+}
+
+fn add_counts(into: &mut BTreeMap<String, u64>, from: &BTreeMap<String, u64>) {
+    for (name, count) in from {
+        *into.entry(name.clone()).or_default() += count;
+    }
+}
+
+/// Contents of `usage_stats.json`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UsageStats {
+    /// Counts by day, keyed `YYYY-MM-DD`
+    #[serde(default)]
+    pub days: BTreeMap<String, DayStats>,
+}
+
+impl UsageStats {
+    /// Read the statistics from `path`; a missing or unreadable file gives
+    /// empty statistics
+    pub fn load(path: &Path) -> Self {
+        match std::fs::read_to_string(path) {
+            Ok(json) => serde_json::from_str(&json).unwrap_or_else(|e| {
+                tracing::warn!("Ignoring invalid usage statistics file {:?}: {}", path, e);
+                Self::default()
+            }),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Self::default(),
+            Err(e) => {
+                tracing::warn!("Failed to read usage statistics file {:?}: {}", path, e);
+                Self::default()
+            }
+        }
+    }
+
+    /// Write the statistics to `path` without indentation, creating its
+    /// directory if needed
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let json = serde_json::to_string(self).map_err(io::Error::other)?;
+        std::fs::write(path, json)
+    }
+
+    /// The statistics as indented JSON, for exporting
+    pub fn to_json_pretty(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+
+    /// Whether nothing was counted
+    pub fn is_empty(&self) -> bool {
+        self.days.values().all(DayStats::is_empty)
+    }
+
+    /// The counts of `day`, created if needed
+    pub fn day_mut(&mut self, day: NaiveDate) -> &mut DayStats {
+        self.days
+            .entry(day.format(DAY_FORMAT).to_string())
+            .or_default()
+    }
+
+    /// Count a run of the command `name` on `day`
+    pub fn record_command(&mut self, day: NaiveDate, name: &str) {
+        *self
+            .day_mut(day)
+            .commands
+            .entry(name.to_string())
+            .or_default() += 1;
+    }
+
+    /// Count a key pressed on `day`
+    pub fn record_key(&mut self, day: NaiveDate) {
+        self.day_mut(day).keys += 1;
+    }
+
+    /// Add `secs` seconds of focused time to `day`
+    pub fn record_focus(&mut self, day: NaiveDate, secs: u64) {
+        if secs > 0 {
+            self.day_mut(day).focused_secs += secs;
+        }
+    }
+
+    /// Count the file `path` in `language` opened on `day`
+    pub fn record_file(&mut self, day: NaiveDate, path: &str, language: &str) {
+        let stats = self.day_mut(day);
+        *stats.files.entry(path.to_string()).or_default() += 1;
+        *stats.languages.entry(language.to_string()).or_default() += 1;
+    }
+
+    /// Add the counts of `other`, day by day
+    pub fn merge(&mut self, other: &UsageStats) {
+        for (day, stats) in &other.days {
+            self.days.entry(day.clone()).or_default().merge(stats);
+        }
+    }
+
+    /// Keep only the `retention_days` days up to and including `today`
+    ///
+    /// Days that don't parse as dates are dropped too.
+    pub fn prune(&mut self, today: NaiveDate, retention_days: u32) {
+        let first = first_kept_day(today, retention_days);
+        self.days.retain(|day, _| {
+            NaiveDate::parse_from_str(day, DAY_FORMAT)
+                .is_ok_and(|day| first.is_some_and(|first| day >= first))
+        });
+    }
+
+    /// The counts of all kept days added together
+    pub fn totals(&self) -> DayStats {
+        let mut totals = DayStats::default();
+        for stats in self.days.values() {
+            totals.merge(stats);
+        }
+        totals
+    }
+
+    /// Keys pressed on each of the `days` days up to and including `today`,
+    /// oldest first, with 0 for days without counts
+    pub fn daily_keys(&self, today: NaiveDate, days: u32) -> Vec<u64> {
+        (0..days)
+            .rev()
+            .map(|back| {
+                today
+                    .checked_sub_days(Days::new(back as u64))
+                    .and_then(|day| self.days.get(&day.format(DAY_FORMAT).to_string()))
+                    .map_or(0, |stats| stats.keys)
+            })
+            .collect()
+    }
+}
+
+/// First day inside a retention window ending `today`; `None` keeps no days
+fn first_kept_day(today: NaiveDate, retention_days: u32) -> Option<NaiveDate> {
+    let back = (retention_days as u64).checked_sub(1)?;
+    Some(
+        today
+            .checked_sub_days(Days::new(back))
+            .unwrap_or(NaiveDate::MIN),
+    )
+}
+
+/// The counts of `counts`, largest first, ties by name, at most `limit`
+pub fn top_counts(counts: &BTreeMap<String, u64>, limit: usize) -> Vec<(&str, u64)> {
+    let mut sorted: Vec<(&str, u64)> = counts.iter().map(|(k, v)| (k.as_str(), *v)).collect();
+    sorted.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+    sorted.truncate(limit);
+    sorted
+}
+
+/// One ASCII character per value, scaled to the largest value; zero is
+/// always the lowest level
+pub fn sparkline(values: &[u64]) -> String {
+    let max = values.iter().copied().max().unwrap_or(0);
+    let top = (SPARK_LEVELS.len() - 1) as u64;
+    values
+        .iter()
+        .map(|&value| {
+            let level = if value == 0 || max == 0 {
+                0
+            } else {
+                // Any activity lands above the lowest level
+                1 + value * (top - 1) / max
+            };
+            SPARK_LEVELS[level as usize] as char
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, DAY_FORMAT).unwrap()
+    }
+
+    #[test]
+    fn test_counts_accumulate_per_day() {
+        let mut stats = UsageStats::default();
+        let day = date("2026-03-01");
+        stats.record_command(day, "Save");
+        stats.record_command(day, "Save");
+        stats.record_command(date("2026-03-02"), "Undo");
+        stats.record_key(day);
+        stats.record_focus(day, 30);
+        stats.record_focus(day, 0);
+        stats.record_file(day, "/p/a.rs", "rust");
+        stats.record_file(day, "/p/b.rs", "rust");
+
+        let first = &stats.days["2026-03-01"];
+        assert_eq!(first.commands["Save"], 2);
+        assert_eq!(first.keys, 1);
+        assert_eq!(first.focused_secs, 30);
+        assert_eq!(first.languages["rust"], 2);
+        assert_eq!(stats.days["2026-03-02"].commands["Undo"], 1);
+
+        let totals = stats.totals();
+        assert_eq!(totals.commands.values().sum::<u64>(), 3);
+        assert_eq!(totals.files.len(), 2);
+    }
+
+    #[test]
+    fn test_merge_adds_counts() {
+        let day = date("2026-03-01");
+        let mut stored = UsageStats::default();
+        stored.record_command(day, "Save");
+        stored.record_key(day);
+        let mut pending = UsageStats::default();
+        pending.record_command(day, "Save");
+        pending.record_key(date("2026-03-02"));
+
+        stored.merge(&pending);
+        assert_eq!(stored.days["2026-03-01"].commands["Save"], 2);
+        assert_eq!(stored.days["2026-03-01"].keys, 1);
+        assert_eq!(stored.days["2026-03-02"].keys, 1);
+    }
+
+    #[test]
+    fn test_prune_keeps_retention_window() {
+        let mut stats = UsageStats::default();
+        for day in ["2026-02-27", "2026-02-28", "2026-03-01", "2026-03-02"] {
+            stats.record_key(date(day));
+        }
+        stats
+            .days
+            .insert("garbage".to_string(), DayStats::default());
+
+        stats.prune(date("2026-03-02"), 3);
+        let days: Vec<&str> = stats.days.keys().map(String::as_str).collect();
+        assert_eq!(days, ["2026-02-28", "2026-03-01", "2026-03-02"]);
+
+        stats.prune(date("2026-03-02"), 0);
+        assert!(stats.days.is_empty());
+    }
+
+    #[test]
+    fn test_daily_keys_fill_missing_days() {
+        let mut stats = UsageStats::default();
+        stats.record_key(date("2026-03-01"));
+        stats.record_key(date("2026-03-03"));
+        stats.record_key(date("2026-03-03"));
+        assert_eq!(stats.daily_keys(date("2026-03-03"), 4), [0, 1, 0, 2]);
+    }
+
+    #[test]
+    fn test_sparkline_scales_to_largest_value() {
+        assert_eq!(sparkline(&[]), "");
+        assert_eq!(sparkline(&[0, 0]), "__");
+        assert_eq!(sparkline(&[0, 1, 100]), "_.@");
+        assert_eq!(sparkline(&[0, 50, 100]), "_+@");
+    }
+
+    #[test]
+    fn test_top_counts_orders_by_count_then_name() {
+        let counts: BTreeMap<String, u64> = [("b", 2), ("a", 2), ("c", 5), ("d", 1)]
+            .into_iter()
+            .map(|(k, v)| (k.to_string(), v))
+            .collect();
+        assert_eq!(top_counts(&counts, 3), [("c", 5), ("a", 2), ("b", 2)]);
+    }
+
+    #[test]
+    fn test_save_and_load_round_trip() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("state").join("usage_stats.json");
+        assert_eq!(UsageStats::load(&path), UsageStats::default());
+
+        let mut stats = UsageStats::default();
+        stats.record_command(date("2026-03-01"), "Save");
+        stats.record_file(date("2026-03-01"), "/p/a.rs", "rust");
+        stats.save(&path).unwrap();
+        assert_eq!(UsageStats::load(&path), stats);
+        // Empty counts are left out of the file
+        assert!(!std::fs::read_to_string(&path).unwrap().contains("keys"));
+    }
+}
@@ -16,6 +16,8 @@ use tracing_subscriber::layer::Context;
 use tracing_subscriber::registry::LookupSpan;
 use tracing_subscriber::Layer;
 
+use crate::services::time_source::{RealTimeSource, SharedTimeSource};
+
 /// Deduplication state for warning messages
 struct DeduplicationState {
     /// Map from message hash to (last_seen_time, count)
@@ -24,14 +26,17 @@ struct DeduplicationState {
     window: Duration,
     /// Maximum number of unique messages to track
     max_entries: usize,
+    /// Clock the window is measured on
+    time_source: SharedTimeSource,
 }
 
 impl DeduplicationState {
-    fn new() -> Self {
+    fn new(time_source: SharedTimeSource) -> Self {
         Self {
             recent_messages: HashMap::new(),
             window: Duration::from_secs(5), // Deduplicate within 5 seconds
             max_entries: 100,               // Track up to 100 unique messages
+            time_source,
         }
     }
 
@@ -40,7 +45,7 @@ impl DeduplicationState {
     /// we're logging after having suppressed duplicates
     fn check_message(&mut self, message: &str) -> (bool, usize) {
         let hash = self.hash_message(message);
-        let now = Instant::now();
+        let now = self.time_source.now();
 
         // Clean up old entries periodically
         if self.recent_messages.len() > self.max_entries {
@@ -89,6 +94,20 @@ pub struct WarningLogHandle {
     pub receiver: mpsc::Receiver<()>,
     /// Path to the warning log file
     pub path: PathBuf,
+    /// Deduplication state shared with the layer
+    dedup: Arc<Mutex<DeduplicationState>>,
+}
+
+impl WarningLogHandle {
+    /// Measure the deduplication window on `time_source`
+    ///
+    /// The layer is created before the editor and starts on the real clock;
+    /// the editor hands over its own clock once it exists.
+    pub fn set_time_source(&self, time_source: SharedTimeSource) {
+        if let Ok(mut dedup) = self.dedup.lock() {
+            dedup.time_source = time_source;
+        }
+    }
 }
 
 /// Create a warning log layer and handle
@@ -104,13 +123,20 @@ pub fn create_with_path(path: PathBuf) -> std::io::Result<(WarningLogLayer, Warn
 
     let (sender, receiver) = mpsc::channel();
 
+    let dedup = Arc::new(Mutex::new(
+        DeduplicationState::new(RealTimeSource::shared()),
+    ));
     let layer = WarningLogLayer {
         file: Arc::new(Mutex::new(file)),
         sender,
-        dedup: Arc::new(Mutex::new(DeduplicationState::new())),
+        dedup: Arc::clone(&dedup),
     };
 
-    let handle = WarningLogHandle { receiver, path };
+    let handle = WarningLogHandle {
+        receiver,
+        path,
+        dedup,
+    };
 
     Ok((layer, handle))
 }
@@ -190,3 +216,52 @@ impl tracing::field::Visit for StringVisitor {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::time_source::TestTimeSource;
+
+    #[test]
+    fn test_duplicates_suppressed_within_window() {
+        let time = TestTimeSource::shared();
+        let mut dedup = DeduplicationState::new(time.clone());
+
+        assert_eq!(dedup.check_message("disk full"), (true, 0));
+        time.advance(Duration::from_secs(4));
+        assert_eq!(dedup.check_message("disk full"), (false, 0));
+        assert_eq!(dedup.check_message("other"), (true, 0));
+    }
+
+    #[test]
+    fn test_window_restarts_on_each_duplicate() {
+        let time = TestTimeSource::shared();
+        let mut dedup = DeduplicationState::new(time.clone());
+
+        dedup.check_message("disk full");
+        for _ in 0..3 {
+            time.advance(Duration::from_secs(4));
+            assert_eq!(dedup.check_message("disk full"), (false, 0));
+        }
+
+        // Once quiet for the whole window, it's logged with the count of
+        // the ones suppressed
+        time.advance(Duration::from_secs(5) - Duration::from_nanos(1));
+        assert_eq!(dedup.check_message("disk full"), (false, 0));
+        time.advance(Duration::from_secs(5));
+        assert_eq!(dedup.check_message("disk full"), (true, 4));
+    }
+
+    #[test]
+    fn test_handle_moves_window_to_given_clock() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let (layer, handle) = create_with_path(temp_dir.path().join("warnings.log")).unwrap();
+        let time = TestTimeSource::shared();
+        handle.set_time_source(time.clone());
+
+        let mut dedup = layer.dedup.lock().unwrap();
+        assert_eq!(dedup.check_message("disk full"), (true, 0));
+        time.advance(Duration::from_secs(6));
+        assert_eq!(dedup.check_message("disk full"), (true, 0));
+    }
+}
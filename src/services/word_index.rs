@@ -0,0 +1,632 @@
+//! Identifiers of open buffers, for word completion
+//!
+//! [`WordIndex`] counts how often each identifier occurs in each open buffer
+//! and keeps the totals over all of them. A buffer is read in line-aligned
+//! chunks whose ends depend only on the lines around them, so an edit changes
+//! the chunks it touches and leaves the rest as they were. When a buffer's
+//! revision changes, the next pass hashes its chunks again and only tokenizes
+//! the ones it hasn't seen; words of an edited function are updated without
+//! tokenizing the rest of the file.
+//!
+//! The work is done in [`WordIndex::step`]s, each bounded by a byte budget and
+//! a time budget, so the editor can run one per idle tick without missing a
+//! frame.
+
+use crate::model::buffer::Buffer;
+use crate::model::event::BufferId;
+use crate::primitives::case_matching::{prefix_matches_case, CaseMatching};
+use crate::primitives::word_navigation::is_word_char;
+use crate::services::time_source::TimeSource;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::time::Duration;
+
+/// Chunks are cut at the first suitable line end after this many bytes
+const MIN_CHUNK_BYTES: usize = 512;
+
+/// Chunks are cut here at the latest (at a line end or word boundary if
+/// there is one)
+pub const MAX_CHUNK_BYTES: usize = 8 * 1024;
+
+/// A line end closes a chunk when its line hash has these bits clear
+const CHUNK_MASK: u64 = 0xf;
+
+/// Shorter words aren't worth completing
+const MIN_WORD_LEN: usize = 3;
+
+/// Longer runs are data (hashes, base64), not identifiers
+const MAX_WORD_LEN: usize = 64;
+
+/// Work allowed in one step
+#[derive(Debug, Clone, Copy)]
+pub struct WorkBudget {
+    /// Bytes read from buffers (the first chunk of a step is always read)
+    pub bytes: usize,
+    /// Time spent, checked between chunks
+    pub time: Duration,
+}
+
+/// A completion candidate
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WordCandidate {
+    pub word: String,
+    /// Occurrences in the buffer completion was asked in
+    pub local: u32,
+    /// Occurrences in all indexed buffers
+    pub total: u32,
+}
+
+/// Size of the index
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct WordIndexStats {
+    pub buffers: usize,
+    /// Distinct words over all buffers
+    pub words: usize,
+    /// Rough heap usage in bytes
+    pub memory_bytes: usize,
+}
+
+/// Words of a run of whole lines
+#[derive(Debug, Clone)]
+struct Chunk {
+    /// Hash of the chunk's bytes
+    hash: u64,
+    words: Vec<(String, u32)>,
+}
+
+/// Pass over a buffer whose content changed
+#[derive(Debug, Default)]
+struct Pass {
+    revision: u64,
+    /// Where the next chunk starts
+    offset: usize,
+    chunks: Vec<Chunk>,
+    /// Chunks whose words aren't in the counts yet
+    fresh: Vec<usize>,
+    /// Chunks of the last complete pass not seen again yet, by hash
+    unmatched: HashMap<u64, Vec<Chunk>>,
+    /// Chunks tokenized by abandoned passes, by hash
+    spare: HashMap<u64, Chunk>,
+}
+
+/// Words of one buffer
+#[derive(Debug, Default)]
+struct BufferWords {
+    /// Revision `chunks` were made at
+    revision: Option<u64>,
+    /// Chunks of the last complete pass, in buffer order
+    chunks: Vec<Chunk>,
+    /// Occurrences of each word over `chunks`
+    counts: HashMap<String, u32>,
+    pass: Option<Pass>,
+}
+
+/// Identifier counts of open buffers, updated incrementally
+#[derive(Debug, Default)]
+pub struct WordIndex {
+    buffers: HashMap<BufferId, BufferWords>,
+    /// Occurrences of each word over all buffers
+    totals: HashMap<String, u32>,
+    /// Bytes tokenized since the index was created, for tests and logging
+    tokenized_bytes: usize,
+}
+
+impl WordIndex {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Bring the index closer to the content of `buffers`
+    ///
+    /// Buffers not listed are dropped from the index; listed buffers are
+    /// worked on in order, so the active one should come first. Returns true
+    /// when work is left for another step.
+    pub fn step(
+        &mut self,
+        buffers: &[(BufferId, &Buffer)],
+        budget: WorkBudget,
+        clock: &dyn TimeSource,
+    ) -> bool {
+        self.evict(|id| buffers.iter().any(|(listed, _)| *listed == id));
+
+        let started = clock.now();
+        let mut read = 0;
+        for &(id, buffer) in buffers {
+            loop {
+                let entry = self.buffers.entry(id).or_default();
+                if entry.revision == Some(buffer.revision()) && entry.pass.is_none() {
+                    break;
+                }
+                let out_of_budget = read > 0
+                    && (read + MAX_CHUNK_BYTES > budget.bytes
+                        || clock.elapsed_since(started) >= budget.time);
+                if out_of_budget {
+                    return true;
+                }
+                read += self.index_chunk(id, buffer);
+            }
+        }
+        false
+    }
+
+    /// Drop buffers `keep` says no to
+    pub fn evict(&mut self, keep: impl Fn(BufferId) -> bool) {
+        let closed: Vec<BufferId> = self
+            .buffers
+            .keys()
+            .copied()
+            .filter(|id| !keep(*id))
+            .collect();
+        for id in closed {
+            if let Some(words) = self.buffers.remove(&id) {
+                for (word, count) in &words.counts {
+                    remove_count(&mut self.totals, word, *count);
+                }
+            }
+        }
+    }
+
+    /// Drop everything
+    pub fn clear(&mut self) {
+        self.buffers.clear();
+        self.totals.clear();
+    }
+
+    /// Words starting with `prefix` (ignoring case), best first
+    ///
+    /// If `case` makes the prefix case-sensitive, words starting with it in
+    /// the same case come first. Then words of the `current` buffer, by how
+    /// often they occur in it; words found only in other buffers follow, by
+    /// how often they occur overall. The prefix itself isn't offered.
+    pub fn complete(
+        &self,
+        prefix: &str,
+        current: Option<BufferId>,
+        case: CaseMatching,
+        limit: usize,
+    ) -> Vec<WordCandidate> {
+        if prefix.is_empty() {
+            return Vec::new();
+        }
+        let match_case = case.is_case_sensitive(prefix, false);
+        let lower = prefix.to_lowercase();
+        let local = current
+            .and_then(|id| self.buffers.get(&id))
+            .map(|words| &words.counts);
+        let mut candidates: Vec<WordCandidate> = self
+            .totals
+            .iter()
+            .filter(|(word, _)| word.as_str() != prefix && word.to_lowercase().starts_with(&lower))
+            .map(|(word, &total)| WordCandidate {
+                word: word.clone(),
+                local: local
+                    .and_then(|counts| counts.get(word))
+                    .copied()
+                    .unwrap_or(0),
+                total,
+            })
+            .collect();
+        let same_case = |c: &WordCandidate| match_case && prefix_matches_case(&c.word, prefix);
+        candidates.sort_by(|a, b| {
+            same_case(b)
+                .cmp(&same_case(a))
+                .then((b.local > 0).cmp(&(a.local > 0)))
+                .then(b.local.cmp(&a.local))
+                .then(b.total.cmp(&a.total))
+                .then(a.word.cmp(&b.word))
+        });
+        candidates.truncate(limit);
+        candidates
+    }
+
+    /// Occurrences of `word` in `buffer`, as of the last complete pass
+    pub fn count(&self, buffer: BufferId, word: &str) -> u32 {
+        self.buffers
+            .get(&buffer)
+            .and_then(|words| words.counts.get(word))
+            .copied()
+            .unwrap_or(0)
+    }
+
+    /// Bytes tokenized so far (chunks found unchanged aren't tokenized again)
+    pub fn tokenized_bytes(&self) -> usize {
+        self.tokenized_bytes
+    }
+
+    pub fn stats(&self) -> WordIndexStats {
+        // String header and map slot per entry, plus the text
+        let entry = |word: &String| std::mem::size_of::<(String, u32)>() + 8 + word.len();
+        let counts: usize = self
+            .buffers
+            .values()
+            .map(|words| {
+                let chunk_words: usize = words
+                    .chunks
+                    .iter()
+                    .flat_map(|chunk| &chunk.words)
+                    .map(|(word, _)| entry(word))
+                    .sum();
+                chunk_words + words.counts.keys().map(entry).sum::<usize>()
+            })
+            .sum();
+        WordIndexStats {
+            buffers: self.buffers.len(),
+            words: self.totals.len(),
+            memory_bytes: counts + self.totals.keys().map(entry).sum::<usize>(),
+        }
+    }
+
+    /// Index the next chunk of a buffer, returning the bytes read
+    fn index_chunk(&mut self, id: BufferId, buffer: &Buffer) -> usize {
+        let revision = buffer.revision();
+        let words = self.buffers.entry(id).or_default();
+        let pass = match &mut words.pass {
+            Some(pass) if pass.revision == revision => pass,
+            pass => {
+                // The content changed (again): start over, keeping what the
+                // abandoned pass tokenized
+                let mut spare = HashMap::new();
+                let mut unmatched: HashMap<u64, Vec<Chunk>> = HashMap::new();
+                if let Some(old) = pass.take() {
+                    let fresh: Vec<bool> = (0..old.chunks.len())
+                        .map(|i| old.fresh.contains(&i))
+                        .collect();
+                    for (chunk, fresh) in old.chunks.into_iter().zip(fresh) {
+                        if fresh {
+                            spare.insert(chunk.hash, chunk);
+                        }
+                    }
+                    spare.extend(old.spare);
+                }
+                for chunk in &words.chunks {
+                    unmatched.entry(chunk.hash).or_default().push(chunk.clone());
+                }
+                pass.insert(Pass {
+                    revision,
+                    unmatched,
+                    spare,
+                    ..Default::default()
+                })
+            }
+        };
+
+        let end = (pass.offset + MAX_CHUNK_BYTES).min(buffer.len());
+        let window = buffer.slice_bytes(pass.offset..end);
+        let read = window.len();
+        let at_end = end == buffer.len();
+        let bytes = &window[..chunk_len(&window, at_end)];
+
+        let hash = hash_bytes(bytes);
+        if let Some(chunk) = pass.unmatched.get_mut(&hash).and_then(|same| same.pop()) {
+            pass.chunks.push(chunk);
+        } else {
+            let chunk = match pass.spare.remove(&hash) {
+                Some(chunk) => chunk,
+                None => {
+                    self.tokenized_bytes += bytes.len();
+                    Chunk {
+                        hash,
+                        words: tokenize(bytes),
+                    }
+                }
+            };
+            pass.fresh.push(pass.chunks.len());
+            pass.chunks.push(chunk);
+        }
+        pass.offset += bytes.len();
+
+        // An empty chunk means the buffer is shorter than a chunk being read
+        // (slice failed) or fully read
+        if bytes.is_empty() || pass.offset >= buffer.len() {
+            self.finish_pass(id);
+        }
+        read
+    }
+
+    /// Swap in the chunks of a complete pass, updating the counts
+    fn finish_pass(&mut self, id: BufferId) {
+        let Some(words) = self.buffers.get_mut(&id) else {
+            return;
+        };
+        let Some(pass) = words.pass.take() else {
+            return;
+        };
+        for chunk in pass.unmatched.values().flatten() {
+            for (word, count) in &chunk.words {
+                remove_count(&mut words.counts, word, *count);
+                remove_count(&mut self.totals, word, *count);
+            }
+        }
+        for &i in &pass.fresh {
+            for (word, count) in &pass.chunks[i].words {
+                *words.counts.entry(word.clone()).or_default() += count;
+                *self.totals.entry(word.clone()).or_default() += count;
+            }
+        }
+        words.chunks = pass.chunks;
+        words.revision = Some(pass.revision);
+    }
+}
+
+/// Subtract `count` occurrences of `word`, dropping it at zero
+fn remove_count(counts: &mut HashMap<String, u32>, word: &str, count: u32) {
+    if let Some(n) = counts.get_mut(word) {
+        *n = n.saturating_sub(count);
+        if *n == 0 {
+            counts.remove(word);
+        }
+    }
+}
+
+fn hash_bytes(bytes: &[u8]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    bytes.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Length of the chunk at the start of `window`
+///
+/// A chunk ends after the first line past [`MIN_CHUNK_BYTES`] whose hash has
+/// the [`CHUNK_MASK`] bits clear, so the cut only depends on nearby lines.
+/// Without one, it ends at the last line end (or non-word byte) of a full
+/// window, or at the end of the buffer.
+fn chunk_len(window: &[u8], at_end: bool) -> usize {
+    let mut line_start = 0;
+    for (i, &byte) in window.iter().enumerate() {
+        if byte != b'\n' {
+            continue;
+        }
+        let line_end = i + 1;
+        if line_end >= MIN_CHUNK_BYTES
+            && hash_bytes(&window[line_start..line_end]) & CHUNK_MASK == 0
+        {
+            return line_end;
+        }
+        line_start = line_end;
+    }
+    if at_end {
+        return window.len();
+    }
+    if line_start > 0 {
+        return line_start;
+    }
+    window
+        .iter()
+        .rposition(|&b| !is_word_byte(b))
+        .map_or(window.len(), |i| i + 1)
+}
+
+/// Word characters, with any non-ASCII byte counting as a letter
+fn is_word_byte(byte: u8) -> bool {
+    is_word_char(byte) || byte >= 0x80
+}
+
+/// Identifiers in `bytes` and how often each occurs
+fn tokenize(bytes: &[u8]) -> Vec<(String, u32)> {
+    let mut counts: HashMap<&str, u32> = HashMap::new();
+    let mut start = None;
+    for (i, &byte) in bytes.iter().chain(std::iter::once(&b' ')).enumerate() {
+        match (is_word_byte(byte), start) {
+            (true, None) => start = Some(i),
+            (false, Some(s)) => {
+                start = None;
+                let word = &bytes[s..i];
+                if (MIN_WORD_LEN..=MAX_WORD_LEN).contains(&word.len()) && !word[0].is_ascii_digit()
+                {
+                    if let Ok(word) = std::str::from_utf8(word) {
+                        *counts.entry(word).or_default() += 1;
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    counts
+        .into_iter()
+        .map(|(word, count)| (word.to_string(), count))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::time_source::TestTimeSource;
+    use std::sync::Arc;
+    use std::time::Instant;
+
+    const UNBOUNDED: WorkBudget = WorkBudget {
+        bytes: usize::MAX,
+        time: Duration::from_secs(60),
+    };
+
+    fn buffer(text: &str) -> Buffer {
+        Buffer::from_str_test(text)
+    }
+
+    /// Numbered lines mentioning `word` once each
+    fn lines(word: &str, count: usize) -> String {
+        (0..count)
+            .map(|i| format!("let {}_{} = value;\n", word, i))
+            .collect()
+    }
+
+    /// Clock that moves a millisecond every time it is read
+    #[derive(Debug)]
+    struct TickingClock(Arc<TestTimeSource>);
+
+    impl TimeSource for TickingClock {
+        fn now(&self) -> Instant {
+            self.0.advance(Duration::from_millis(1));
+            self.0.now()
+        }
+
+        fn sleep(&self, duration: Duration) {
+            self.0.sleep(duration)
+        }
+    }
+
+    #[test]
+    fn test_tokenize_skips_short_words_and_numbers() {
+        let mut words = tokenize("fn parse_args(argc: i32) -> 42abc { café }".as_bytes());
+        words.sort();
+        assert_eq!(
+            words,
+            vec![
+                ("argc".to_string(), 1),
+                ("café".to_string(), 1),
+                ("i32".to_string(), 1),
+                ("parse_args".to_string(), 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_edit_reindexes_only_changed_chunks() {
+        let clock = TestTimeSource::new();
+        let id = BufferId(1);
+        let text = format!(
+            "{}fn old_name() {{}}\n{}",
+            lines("alpha", 400),
+            lines("beta", 400)
+        );
+        let mut buf = buffer(&text);
+        let mut index = WordIndex::new();
+        while index.step(&[(id, &buf)], UNBOUNDED, &clock) {}
+        assert_eq!(index.count(id, "old_name"), 1);
+        assert_eq!(index.count(id, "value"), 800);
+        let first_pass = index.tokenized_bytes();
+        assert_eq!(first_pass, text.len());
+
+        let pos = text.find("old_name").unwrap();
+        buf.delete_bytes(pos, "old_name".len());
+        buf.insert(pos, "new_name");
+        while index.step(&[(id, &buf)], UNBOUNDED, &clock) {}
+
+        assert_eq!(index.count(id, "old_name"), 0);
+        assert_eq!(index.count(id, "new_name"), 1);
+        assert_eq!(index.count(id, "value"), 800);
+        assert!(index
+            .complete("old", Some(id), CaseMatching::Smart, 10)
+            .is_empty());
+        let reindexed = index.tokenized_bytes() - first_pass;
+        assert!(reindexed > 0);
+        assert!(
+            reindexed <= 2 * MAX_CHUNK_BYTES,
+            "re-tokenized {} of {} bytes",
+            reindexed,
+            text.len()
+        );
+    }
+
+    #[test]
+    fn test_ranking_prefers_current_buffer_then_frequency() {
+        let clock = TestTimeSource::new();
+        let current = buffer("request request response\n");
+        let other = buffer("requeue requeue requeue requeue request_id\n");
+        let mut index = WordIndex::new();
+        let buffers = [(BufferId(1), &current), (BufferId(2), &other)];
+        while index.step(&buffers, UNBOUNDED, &clock) {}
+
+        let words: Vec<String> = index
+            .complete("req", Some(BufferId(1)), CaseMatching::Smart, 10)
+            .into_iter()
+            .map(|c| c.word)
+            .collect();
+        assert_eq!(words, vec!["request", "requeue", "request_id"]);
+
+        // Matching ignores case, and the prefix itself isn't offered
+        let words: Vec<String> = index
+            .complete("REQUES", Some(BufferId(1)), CaseMatching::Smart, 10)
+            .into_iter()
+            .map(|c| c.word)
+            .collect();
+        assert_eq!(words, vec!["request", "request_id"]);
+        let words: Vec<String> = index
+            .complete("request", Some(BufferId(1)), CaseMatching::Smart, 10)
+            .into_iter()
+            .map(|c| c.word)
+            .collect();
+        assert_eq!(words, vec!["request_id"]);
+
+        // Closing the other buffer drops its words
+        while index.step(&buffers[..1], UNBOUNDED, &clock) {}
+        assert!(index
+            .complete("requeue", Some(BufferId(1)), CaseMatching::Smart, 10)
+            .is_empty());
+        assert_eq!(index.stats().buffers, 1);
+    }
+
+    #[test]
+    fn test_uppercase_prefix_prefers_matching_case() {
+        let clock = TestTimeSource::new();
+        let current = buffer("request request request Request\n");
+        let mut index = WordIndex::new();
+        let buffers = [(BufferId(1), &current)];
+        while index.step(&buffers, UNBOUNDED, &clock) {}
+        let complete = |prefix: &str, case: CaseMatching| -> Vec<String> {
+            index
+                .complete(prefix, Some(BufferId(1)), case, 10)
+                .into_iter()
+                .map(|c| c.word)
+                .collect()
+        };
+
+        // Words in the typed case come first, the others are still offered
+        assert_eq!(
+            complete("Req", CaseMatching::Smart),
+            vec!["Request", "request"]
+        );
+        // A lowercase prefix, or smart case off, ranks by frequency alone
+        assert_eq!(
+            complete("req", CaseMatching::Smart),
+            vec!["request", "Request"]
+        );
+        assert_eq!(
+            complete("Req", CaseMatching::Insensitive),
+            vec!["request", "Request"]
+        );
+    }
+
+    #[test]
+    fn test_step_respects_byte_budget() {
+        let clock = TestTimeSource::new();
+        let text = lines("gamma", 2000);
+        let buf = buffer(&text);
+        let budget = WorkBudget {
+            bytes: 2 * MAX_CHUNK_BYTES,
+            time: Duration::from_secs(60),
+        };
+        let mut index = WordIndex::new();
+        let mut steps = 0;
+        let mut last = 0;
+        while index.step(&[(BufferId(1), &buf)], budget, &clock) {
+            steps += 1;
+            assert!(index.tokenized_bytes() - last <= budget.bytes);
+            last = index.tokenized_bytes();
+        }
+        assert!(steps >= text.len() / budget.bytes - 1);
+        assert_eq!(index.count(BufferId(1), "value"), 2000);
+    }
+
+    #[test]
+    fn test_step_respects_time_budget() {
+        let clock = TickingClock(TestTimeSource::shared());
+        let text = lines("delta", 2000);
+        let buf = buffer(&text);
+        let budget = WorkBudget {
+            bytes: usize::MAX,
+            time: Duration::from_millis(3),
+        };
+        let mut index = WordIndex::new();
+        let before = clock.0.elapsed();
+        assert!(index.step(&[(BufferId(1), &buf)], budget, &clock));
+        // One read to start, then one per chunk until the deadline
+        assert!(clock.0.elapsed() - before <= Duration::from_millis(5));
+        assert!(index.tokenized_bytes() < text.len());
+
+        while index.step(&[(BufferId(1), &buf)], budget, &clock) {}
+        assert_eq!(index.count(BufferId(1), "value"), 2000);
+        assert_eq!(index.tokenized_bytes(), text.len());
+    }
+}
@@ -0,0 +1,448 @@
+//! Editor-owned worker pool for background work
+//!
+//! Background computation (release checks, indexing, anything too slow for the
+//! main loop) is submitted here instead of each subsystem spawning its own
+//! threads. This caps the total thread count and gives one place to observe
+//! queue latency.
+//!
+//! - Jobs are queued by priority ([`JobPriority`]); equal priorities run in
+//!   submission order
+//! - Each job gets a [`CancellationToken`] and should check it periodically;
+//!   jobs cancelled before they start never run
+//! - A job may return an [`AsyncMessage`], which is delivered through the async
+//!   bridge so the result wakes the main loop like any other async event
+//!
+//! I/O-bound async work (LSP, file system) stays on the tokio runtime.
+
+use crate::services::async_bridge::AsyncMessage;
+use std::cmp::Ordering as CmpOrdering;
+use std::collections::BinaryHeap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc, Condvar, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+/// Queue wait above which a job start is logged at debug level
+const SLOW_QUEUE_WAIT: Duration = Duration::from_millis(100);
+
+/// Priority of a background job
+///
+/// Higher priorities are always dequeued first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum JobPriority {
+    /// Work nobody is waiting on (release checks, project indexing)
+    Background,
+    /// Regular work whose result shows up eventually (diagnostics processing)
+    Normal,
+    /// Work the user is waiting on right now (highlighting the viewport)
+    UserBlocking,
+}
+
+/// Cooperative cancellation flag shared between a job and its handle
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    /// Create a token that is not cancelled
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Request cancellation
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+
+    /// Whether cancellation was requested
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::SeqCst)
+    }
+}
+
+/// Handle to a submitted job
+#[derive(Debug, Clone)]
+pub struct JobHandle {
+    token: CancellationToken,
+    finished: Arc<AtomicBool>,
+}
+
+impl JobHandle {
+    /// Cancel the job: it won't start if still queued, and a running job
+    /// sees its token cancelled. Its result is discarded either way.
+    pub fn cancel(&self) {
+        self.token.cancel();
+    }
+
+    /// Whether the job was cancelled
+    pub fn is_cancelled(&self) -> bool {
+        self.token.is_cancelled()
+    }
+
+    /// Whether the job has run to completion or was dropped after cancellation
+    pub fn is_finished(&self) -> bool {
+        self.finished.load(Ordering::SeqCst)
+    }
+}
+
+type Job = Box<dyn FnOnce(&CancellationToken) -> Option<AsyncMessage> + Send>;
+
+struct QueuedJob {
+    name: &'static str,
+    priority: JobPriority,
+    /// Submission order, for FIFO within a priority
+    seq: u64,
+    enqueued_at: Instant,
+    token: CancellationToken,
+    finished: Arc<AtomicBool>,
+    job: Job,
+}
+
+impl PartialEq for QueuedJob {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == CmpOrdering::Equal
+    }
+}
+
+impl Eq for QueuedJob {}
+
+impl PartialOrd for QueuedJob {
+    fn partial_cmp(&self, other: &Self) -> Option<CmpOrdering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for QueuedJob {
+    fn cmp(&self, other: &Self) -> CmpOrdering {
+        // BinaryHeap is a max-heap: higher priority first, then lower seq first
+        self.priority
+            .cmp(&other.priority)
+            .then_with(|| other.seq.cmp(&self.seq))
+    }
+}
+
+#[derive(Default)]
+struct Queue {
+    jobs: BinaryHeap<QueuedJob>,
+    next_seq: u64,
+    shutdown: bool,
+}
+
+struct Shared {
+    queue: Mutex<Queue>,
+    available: Condvar,
+    async_sender: Option<mpsc::Sender<AsyncMessage>>,
+}
+
+/// Stops the workers once the last `WorkerPool` clone is dropped
+struct ShutdownOnDrop {
+    shared: Arc<Shared>,
+    workers: Vec<JoinHandle<()>>,
+}
+
+impl Drop for ShutdownOnDrop {
+    fn drop(&mut self) {
+        let mut queue = self.shared.queue.lock().unwrap();
+        queue.shutdown = true;
+        for job in queue.jobs.drain() {
+            job.token.cancel();
+            job.finished.store(true, Ordering::SeqCst);
+        }
+        drop(queue);
+        self.shared.available.notify_all();
+        // Workers are not joined: a long-running job shouldn't hold up exit
+        self.workers.clear();
+    }
+}
+
+/// A fixed set of worker threads running prioritized, cancellable jobs
+///
+/// Cloning is cheap and shares the same workers; they stop when the last clone
+/// is dropped.
+#[derive(Clone)]
+pub struct WorkerPool {
+    shared: Arc<Shared>,
+    _owner: Arc<ShutdownOnDrop>,
+}
+
+impl WorkerPool {
+    /// Create a pool with `threads` workers (at least one)
+    ///
+    /// Messages returned by jobs are sent to `async_sender`; without one they
+    /// are dropped.
+    pub fn new(threads: usize, async_sender: Option<mpsc::Sender<AsyncMessage>>) -> Self {
+        let shared = Arc::new(Shared {
+            queue: Mutex::new(Queue::default()),
+            available: Condvar::new(),
+            async_sender,
+        });
+
+        let workers = (0..threads.max(1))
+            .filter_map(|i| {
+                let shared = Arc::clone(&shared);
+                thread::Builder::new()
+                    .name(format!("editor-worker-{}", i))
+                    .spawn(move || worker_loop(&shared))
+                    .map_err(|e| tracing::warn!("Failed to spawn worker thread: {}", e))
+                    .ok()
+            })
+            .collect();
+
+        Self {
+            _owner: Arc::new(ShutdownOnDrop {
+                shared: Arc::clone(&shared),
+                workers,
+            }),
+            shared,
+        }
+    }
+
+    /// Default worker count: a small pool, leaving cores for the main loop
+    pub fn default_thread_count() -> usize {
+        thread::available_parallelism()
+            .map(|n| n.get().saturating_sub(1).clamp(1, 4))
+            .unwrap_or(2)
+    }
+
+    /// Queue a job
+    ///
+    /// `name` identifies the job in traces. The job receives its cancellation
+    /// token; a returned message is delivered unless the job was cancelled.
+    pub fn submit<F>(&self, name: &'static str, priority: JobPriority, job: F) -> JobHandle
+    where
+        F: FnOnce(&CancellationToken) -> Option<AsyncMessage> + Send + 'static,
+    {
+        let token = CancellationToken::new();
+        let finished = Arc::new(AtomicBool::new(false));
+        let handle = JobHandle {
+            token: token.clone(),
+            finished: Arc::clone(&finished),
+        };
+
+        let mut queue = self.shared.queue.lock().unwrap();
+        if queue.shutdown {
+            token.cancel();
+            finished.store(true, Ordering::SeqCst);
+            return handle;
+        }
+        let seq = queue.next_seq;
+        queue.next_seq += 1;
+        queue.jobs.push(QueuedJob {
+            name,
+            priority,
+            seq,
+            enqueued_at: Instant::now(),
+            token,
+            finished,
+            job: Box::new(job),
+        });
+        drop(queue);
+        self.shared.available.notify_one();
+
+        handle
+    }
+
+    /// Number of jobs waiting for a worker
+    pub fn queued_jobs(&self) -> usize {
+        self.shared.queue.lock().unwrap().jobs.len()
+    }
+}
+
+fn worker_loop(shared: &Shared) {
+    loop {
+        let job = {
+            let mut queue = shared.queue.lock().unwrap();
+            loop {
+                if queue.shutdown {
+                    return;
+                }
+                if let Some(job) = queue.jobs.pop() {
+                    break job;
+                }
+                queue = shared.available.wait(queue).unwrap();
+            }
+        };
+
+        if job.token.is_cancelled() {
+            tracing::trace!("Worker pool: skipping cancelled job '{}'", job.name);
+            job.finished.store(true, Ordering::SeqCst);
+            continue;
+        }
+
+        let waited = job.enqueued_at.elapsed();
+        if waited >= SLOW_QUEUE_WAIT {
+            tracing::debug!(
+                "Worker pool: job '{}' ({:?}) waited {:?} in queue",
+                job.name,
+                job.priority,
+                waited
+            );
+        } else {
+            tracing::trace!(
+                "Worker pool: job '{}' ({:?}) waited {:?} in queue",
+                job.name,
+                job.priority,
+                waited
+            );
+        }
+
+        let started = Instant::now();
+        let message = (job.job)(&job.token);
+        tracing::trace!(
+            "Worker pool: job '{}' ran for {:?}",
+            job.name,
+            started.elapsed()
+        );
+
+        if let (Some(message), Some(sender)) = (message, &shared.async_sender) {
+            if !job.token.is_cancelled() {
+                let _ = sender.send(message);
+            }
+        }
+        job.finished.store(true, Ordering::SeqCst);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn wait_for(mut condition: impl FnMut() -> bool) -> bool {
+        let start = Instant::now();
+        while start.elapsed() < Duration::from_secs(5) {
+            if condition() {
+                return true;
+            }
+            thread::sleep(Duration::from_millis(5));
+        }
+        false
+    }
+
+    #[test]
+    fn test_priority_order_under_load() {
+        let pool = WorkerPool::new(1, None);
+        let order = Arc::new(Mutex::new(Vec::new()));
+
+        // Keep the only worker busy while the queue fills up
+        let (release_tx, release_rx) = mpsc::channel::<()>();
+        let blocker = pool.submit("blocker", JobPriority::Normal, move |_| {
+            let _ = release_rx.recv();
+            None
+        });
+        assert!(wait_for(|| pool.queued_jobs() == 0));
+
+        let jobs = [
+            ("background-1", JobPriority::Background),
+            ("normal-1", JobPriority::Normal),
+            ("user-1", JobPriority::UserBlocking),
+            ("background-2", JobPriority::Background),
+            ("user-2", JobPriority::UserBlocking),
+            ("normal-2", JobPriority::Normal),
+        ];
+        let handles: Vec<_> = jobs
+            .into_iter()
+            .map(|(name, priority)| {
+                let order = Arc::clone(&order);
+                pool.submit(name, priority, move |_| {
+                    order.lock().unwrap().push(name);
+                    None
+                })
+            })
+            .collect();
+
+        release_tx.send(()).unwrap();
+        assert!(wait_for(|| handles.iter().all(JobHandle::is_finished)));
+        assert!(blocker.is_finished());
+        assert_eq!(
+            *order.lock().unwrap(),
+            vec![
+                "user-1",
+                "user-2",
+                "normal-1",
+                "normal-2",
+                "background-1",
+                "background-2"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_cancel_stops_running_job() {
+        let pool = WorkerPool::new(1, None);
+        let (started_tx, started_rx) = mpsc::channel();
+
+        // A synthetic task that would run for a minute unless cancelled
+        let handle = pool.submit("spin", JobPriority::Background, move |token| {
+            let _ = started_tx.send(());
+            let deadline = Instant::now() + Duration::from_secs(60);
+            while !token.is_cancelled() && Instant::now() < deadline {
+                thread::sleep(Duration::from_millis(1));
+            }
+            None
+        });
+
+        started_rx.recv().unwrap();
+        assert!(!handle.is_finished());
+
+        let cancelled_at = Instant::now();
+        handle.cancel();
+        assert!(wait_for(|| handle.is_finished()));
+        assert!(cancelled_at.elapsed() < Duration::from_secs(5));
+        assert!(handle.is_cancelled());
+    }
+
+    #[test]
+    fn test_cancelled_job_is_skipped_and_not_delivered() {
+        let (tx, rx) = mpsc::channel();
+        let pool = WorkerPool::new(1, Some(tx));
+
+        let (release_tx, release_rx) = mpsc::channel::<()>();
+        pool.submit("blocker", JobPriority::Normal, move |_| {
+            let _ = release_rx.recv();
+            None
+        });
+
+        let ran = Arc::new(AtomicBool::new(false));
+        let ran_clone = Arc::clone(&ran);
+        let cancelled = pool.submit("cancelled", JobPriority::Normal, move |_| {
+            ran_clone.store(true, Ordering::SeqCst);
+            Some(AsyncMessage::LspInitialized {
+                language: "cancelled".to_string(),
+            })
+        });
+        let delivered = pool.submit("delivered", JobPriority::Normal, |_| {
+            Some(AsyncMessage::LspInitialized {
+                language: "delivered".to_string(),
+            })
+        });
+
+        cancelled.cancel();
+        release_tx.send(()).unwrap();
+        assert!(wait_for(
+            || delivered.is_finished() && cancelled.is_finished()
+        ));
+        assert!(!ran.load(Ordering::SeqCst));
+
+        let messages: Vec<_> = rx.try_iter().collect();
+        assert_eq!(messages.len(), 1);
+        assert!(matches!(
+            &messages[0],
+            AsyncMessage::LspInitialized { language } if language == "delivered"
+        ));
+    }
+
+    #[test]
+    fn test_drop_cancels_queued_jobs() {
+        let pool = WorkerPool::new(1, None);
+        let (release_tx, release_rx) = mpsc::channel::<()>();
+        pool.submit("blocker", JobPriority::Normal, move |_| {
+            let _ = release_rx.recv();
+            None
+        });
+        let queued = pool.submit("queued", JobPriority::Normal, |_| None);
+
+        drop(pool);
+        assert!(queued.is_cancelled());
+        assert!(queued.is_finished());
+        let _ = release_tx.send(());
+    }
+}
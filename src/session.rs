@@ -6,10 +6,11 @@
 //! - File explorer state
 //! - Search/replace history and options
 //! - Bookmarks
+//! - Plugin panels created with `persistent: true`
 //!
 //! ## Storage
 //!
-//! Sessions are stored in `$XDG_DATA_HOME/fresh/sessions/{encoded_path}.json`
+//! Sessions are stored in `$XDG_STATE_HOME/fresh/sessions/{encoded_path}.json`
 //! where `{encoded_path}` is the working directory path with:
 //! - Path separators (`/`) replaced with underscores (`_`)
 //! - Special characters percent-encoded as `%XX`
@@ -22,17 +23,57 @@
 //!
 //! Uses atomic writes: write to temp file, then rename.
 //! This ensures the session file is never left in a corrupted state.
+//!
+//! ## Versions
+//!
+//! Every session file records the format `version` it was written with.
+//! Older files are upgraded step by step through `MIGRATIONS` before they
+//! are read, and fields added since are filled in with serde defaults. A file
+//! written by a newer version is refused with [`SessionError::VersionTooNew`]
+//! rather than misread, and is kept under its own backup name when this
+//! version saves over it.
+//!
+//! Before a session file is overwritten, the previous one is copied to
+//! `{encoded_path}.json.bak`.
+//!
+//! ## Incremental Saves
+//!
+//! The editor saves through a [`SessionWrite`], written on a worker thread.
+//! Its sections, the session file and each open file's cursor and scroll
+//! state, are only written when they differ from what is on disk, so saving
+//! a session that hasn't changed writes nothing.
+//!
+//! ## Snapshots
+//!
+//! Every save that changes the session file also writes a timestamped copy to
+//! `sessions/snapshots/{encoded_path}/{unix_millis}.json`, keeping the newest
+//! few. They back "Restore Session from Snapshot", and replace a session file
+//! that can no longer be read.
+//!
+//! ## Named Layouts
+//!
+//! Split arrangements saved with "Save Layout As" are kept per project in
+//! `sessions/layouts/{encoded_path}.json`, along with the layout that was
+//! replaced by the last switch.
 
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use std::collections::HashMap;
 use std::io::{self, Write};
 use std::path::{Path, PathBuf};
 use std::time::{SystemTime, UNIX_EPOCH};
 
-use crate::input::input_history::get_data_dir;
-
 /// Current session file format version
-pub const SESSION_VERSION: u32 = 1;
+///
+/// Bump it when older files need converting to be read correctly, and add
+/// the conversion to `MIGRATIONS`.
+pub const SESSION_VERSION: u32 = 2;
+
+/// Upgrade steps for old session files, as `(from version, upgrade)`
+///
+/// A file at version `n` goes through every step from `n` on, in order, on
+/// the JSON before it is deserialized.
+const MIGRATIONS: &[(u32, fn(&mut Value))] = &[(1, migrate_v1_to_v2)];
 
 /// Current per-file session version
 pub const FILE_SESSION_VERSION: u32 = 1;
@@ -40,7 +81,7 @@ pub const FILE_SESSION_VERSION: u32 = 1;
 /// Persisted session state for a working directory
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Session {
-    /// Schema version for future migrations
+    /// Format version the file was written with (see [`SESSION_VERSION`])
     pub version: u32,
 
     /// Working directory this session belongs to (for validation)
@@ -53,6 +94,7 @@ pub struct Session {
     pub active_split_id: usize,
 
     /// Per-split view states (keyed by split_id)
+    #[serde(default)]
     pub split_states: HashMap<usize, SerializedSplitViewState>,
 
     /// Editor config overrides (toggles that differ from defaults)
@@ -60,6 +102,7 @@ pub struct Session {
     pub config_overrides: SessionConfigOverrides,
 
     /// File explorer state
+    #[serde(default)]
     pub file_explorer: FileExplorerState,
 
     /// Input histories (search, replace, command palette, etc.)
@@ -78,7 +121,20 @@ pub struct Session {
     #[serde(default)]
     pub terminals: Vec<SerializedTerminalSession>,
 
+    /// Persistent plugin panels (restored as placeholders the plugin refills)
+    #[serde(default)]
+    pub panels: Vec<SerializedPanel>,
+
+    /// Pinned file buffers (relative to the working directory)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub pinned_files: Vec<PathBuf>,
+
+    /// Whether distraction-free mode was on (only saved when the config opts in)
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub distraction_free: bool,
+
     /// Timestamp when session was saved (Unix epoch seconds)
+    #[serde(default)]
     pub saved_at: u64,
 }
 
@@ -94,6 +150,10 @@ pub enum SerializedSplitNode {
         terminal_index: usize,
         split_id: usize,
     },
+    Panel {
+        panel_index: usize,
+        split_id: usize,
+    },
     Split {
         direction: SerializedSplitDirection,
         first: Box<SerializedSplitNode>,
@@ -103,6 +163,18 @@ pub enum SerializedSplitNode {
     },
 }
 
+impl SerializedSplitNode {
+    /// Number of leaf splits in the tree
+    pub fn leaf_count(&self) -> usize {
+        match self {
+            SerializedSplitNode::Split { first, second, .. } => {
+                first.leaf_count() + second.leaf_count()
+            }
+            _ => 1,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub enum SerializedSplitDirection {
     Horizontal,
@@ -144,6 +216,26 @@ pub struct SerializedSplitViewState {
     /// Compose width if in compose mode
     #[serde(default)]
     pub compose_width: Option<u16>,
+
+    /// Whether edits typed in this split are refused
+    #[serde(default)]
+    pub input_locked: bool,
+
+    /// Last search made in this split
+    #[serde(default)]
+    pub search: Option<SerializedSearch>,
+}
+
+/// A split's search, re-run on restore
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SerializedSearch {
+    pub query: String,
+    #[serde(default)]
+    pub case_sensitive: bool,
+    #[serde(default)]
+    pub whole_word: bool,
+    #[serde(default)]
+    pub use_regex: bool,
 }
 
 /// Per-file state within a split
@@ -157,6 +249,7 @@ pub struct SerializedFileState {
     pub additional_cursors: Vec<SerializedCursor>,
 
     /// Scroll position (byte offset)
+    #[serde(default)]
     pub scroll: SerializedScroll,
 }
 
@@ -172,9 +265,10 @@ pub struct SerializedCursor {
     pub sticky_column: usize,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct SerializedScroll {
     /// Top visible position as byte offset
+    #[serde(default)]
     pub top_byte: usize,
     /// Virtual line offset within the top line (for wrapped lines)
     #[serde(default)]
@@ -212,6 +306,7 @@ pub struct SessionConfigOverrides {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FileExplorerState {
+    #[serde(default)]
     pub visible: bool,
     #[serde(default)]
     pub width_percent: f32,
@@ -247,6 +342,9 @@ pub struct SessionHistories {
     pub goto_line: Vec<String>,
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub open_file: Vec<String>,
+    /// Clipboard history, newest first (only saved when the config opts in)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub clipboard: Vec<String>,
 }
 
 /// Search options that persist across searches within a session
@@ -271,11 +369,12 @@ pub struct SerializedBookmark {
     pub position: usize,
 }
 
-/// Reference to an open tab (file path or terminal index)
+/// Reference to an open tab (file path, terminal index or panel index)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum SerializedTabRef {
     File(PathBuf),
     Terminal(usize),
+    Panel(usize),
 }
 
 /// Persisted metadata for a terminal session
@@ -290,6 +389,26 @@ pub struct SerializedTerminalSession {
     pub backing_path: PathBuf,
 }
 
+/// Persisted metadata for a plugin panel created with `persistent: true`
+///
+/// Only the panel's identity is saved; its content is recreated by the
+/// owning plugin through the `panel_restore` hook.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SerializedPanel {
+    pub panel_index: usize,
+    /// Plugin that created the panel
+    pub plugin: String,
+    /// The plugin's panel_id
+    pub panel_id: String,
+    /// Buffer name (e.g. "*Diagnostics*")
+    pub name: String,
+    /// Buffer mode for keybindings
+    pub mode: String,
+    /// Whether the panel's buffer was pinned
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub pinned: bool,
+}
+
 // ============================================================================
 // Global file state persistence (per-file, not per-project)
 // ============================================================================
@@ -297,7 +416,7 @@ pub struct SerializedTerminalSession {
 /// Individual file state stored in its own file
 ///
 /// Each source file's scroll/cursor state is stored in a separate JSON file
-/// at `$XDG_DATA_HOME/fresh/file_states/{encoded_path}.json`.
+/// at `$XDG_STATE_HOME/fresh/file_states/{encoded_path}.json`.
 /// This allows concurrent editors to safely update different files without
 /// conflicts.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -333,32 +452,27 @@ impl PersistedFileState {
 /// will restore the same scroll/cursor position.
 ///
 /// Each file's state is stored in a separate JSON file at
-/// `$XDG_DATA_HOME/fresh/file_states/{encoded_path}.json` to avoid conflicts
+/// `$XDG_STATE_HOME/fresh/file_states/{encoded_path}.json` to avoid conflicts
 /// between concurrent editors. States are loaded lazily when opening files
 /// and saved immediately when closing files or saving the session.
 pub struct PersistedFileSession;
 
 impl PersistedFileSession {
-    /// Get the directory for file state files
-    fn states_dir() -> io::Result<PathBuf> {
-        Ok(get_data_dir()?.join("file_states"))
-    }
-
     /// Get the state file path for a source file
-    fn state_file_path(source_path: &Path) -> io::Result<PathBuf> {
+    fn state_file_path(states_dir: &Path, source_path: &Path) -> PathBuf {
         let canonical = source_path
             .canonicalize()
             .unwrap_or_else(|_| source_path.to_path_buf());
         let filename = format!("{}.json", encode_path_for_filename(&canonical));
-        Ok(Self::states_dir()?.join(filename))
+        states_dir.join(filename)
     }
 
     /// Load the state for a file by its absolute path (from disk)
-    pub fn load(path: &Path) -> Option<SerializedFileState> {
-        let state_path = match Self::state_file_path(path) {
-            Ok(p) => p,
-            Err(_) => return None,
-        };
+    ///
+    /// `states_dir` is the directory of file states, see
+    /// [`DirectoryContext::file_states_dir`](crate::config_io::DirectoryContext::file_states_dir).
+    pub fn load(states_dir: &Path, path: &Path) -> Option<SerializedFileState> {
+        let state_path = Self::state_file_path(states_dir, path);
 
         if !state_path.exists() {
             return None;
@@ -383,20 +497,28 @@ impl PersistedFileSession {
     }
 
     /// Save the state for a file by its absolute path (to disk, atomic write)
-    pub fn save(path: &Path, state: SerializedFileState) {
-        let state_path = match Self::state_file_path(path) {
-            Ok(p) => p,
-            Err(e) => {
-                tracing::warn!("Failed to get state path for {:?}: {}", path, e);
-                return;
-            }
-        };
+    ///
+    /// A state equal to the saved one is not written again. Returns whether
+    /// the file was written.
+    pub fn save(states_dir: &Path, path: &Path, state: SerializedFileState) -> bool {
+        let state_path = Self::state_file_path(states_dir, path);
+
+        // Rewriting an unchanged state would only move its timestamp
+        let unchanged = std::fs::read(&state_path)
+            .ok()
+            .and_then(|content| serde_json::from_slice::<PersistedFileState>(&content).ok())
+            .is_some_and(|saved| {
+                saved.version == FILE_SESSION_VERSION && same_json(&saved.state, &state)
+            });
+        if unchanged {
+            return false;
+        }
 
         // Ensure directory exists
         if let Some(parent) = state_path.parent() {
             if let Err(e) = std::fs::create_dir_all(parent) {
                 tracing::warn!("Failed to create state dir: {}", e);
-                return;
+                return false;
             }
         }
 
@@ -405,38 +527,93 @@ impl PersistedFileSession {
             Ok(c) => c,
             Err(e) => {
                 tracing::warn!("Failed to serialize file state: {}", e);
-                return;
+                return false;
             }
         };
 
-        // Write atomically: temp file + rename
-        let temp_path = state_path.with_extension("json.tmp");
-
-        let write_result = (|| -> io::Result<()> {
-            let mut file = std::fs::File::create(&temp_path)?;
-            file.write_all(content.as_bytes())?;
-            file.sync_all()?;
-            std::fs::rename(&temp_path, &state_path)?;
-            Ok(())
-        })();
-
-        if let Err(e) = write_result {
+        if let Err(e) = write_atomic(&state_path, content.as_bytes()) {
             tracing::warn!("Failed to save file state for {:?}: {}", path, e);
+            false
         } else {
             tracing::trace!("File state saved for {:?}", path);
+            true
         }
     }
 }
 
+/// Whether `a` and `b` serialize to the same JSON
+fn same_json<T: Serialize>(a: &T, b: &T) -> bool {
+    match (serde_json::to_value(a), serde_json::to_value(b)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => false,
+    }
+}
+
 // ============================================================================
-// Session file management
+// Incremental session saves
 // ============================================================================
 
-/// Get the sessions directory
-pub fn get_sessions_dir() -> io::Result<PathBuf> {
-    Ok(get_data_dir()?.join("sessions"))
+/// A session save, captured on the main thread and written on a worker
+///
+/// Only the sections that differ from what is on disk are written: the
+/// session file, along with its backup and a snapshot, and the state file of
+/// each open file.
+#[derive(Debug, Clone)]
+pub struct SessionWrite {
+    pub session: Session,
+    /// Cursor and scroll state of the open files, by absolute path
+    pub file_states: Vec<(PathBuf, SerializedFileState)>,
+    /// Number of snapshots to keep, 0 for none
+    pub snapshots: usize,
+    /// Total size the kept snapshots may take
+    pub snapshots_max_bytes: usize,
+}
+
+/// What a [`SessionWrite`] wrote
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SessionWriteSummary {
+    /// Whether the session file changed
+    pub session_written: bool,
+    /// Number of file state files written
+    pub file_states_written: usize,
+}
+
+impl SessionWrite {
+    /// Write the sections that changed
+    ///
+    /// `states_dir` is the directory of file states, see
+    /// [`DirectoryContext::file_states_dir`](crate::config_io::DirectoryContext::file_states_dir).
+    pub fn write(
+        &self,
+        sessions_dir: &Path,
+        states_dir: &Path,
+    ) -> Result<SessionWriteSummary, SessionError> {
+        let file_states_written = self
+            .file_states
+            .iter()
+            .filter(|(path, state)| PersistedFileSession::save(states_dir, path, state.clone()))
+            .count();
+
+        let session_written = self.session.save_if_changed(sessions_dir)?;
+        if session_written && self.snapshots > 0 {
+            if let Err(e) =
+                self.session
+                    .save_snapshot(sessions_dir, self.snapshots, self.snapshots_max_bytes)
+            {
+                tracing::warn!("Failed to save session snapshot: {}", e);
+            }
+        }
+        Ok(SessionWriteSummary {
+            session_written,
+            file_states_written,
+        })
+    }
 }
 
+// ============================================================================
+// Session file management
+// ============================================================================
+
 /// Encode a path into a filesystem-safe filename using percent encoding
 ///
 /// Keeps alphanumeric chars, `-`, `.`, `_` as-is.
@@ -524,12 +701,329 @@ pub fn decode_filename_to_path(encoded: &str) -> Option<PathBuf> {
 }
 
 /// Get the session file path for a working directory
-pub fn get_session_path(working_dir: &Path) -> io::Result<PathBuf> {
+///
+/// `sessions_dir` is the directory of session files, see
+/// [`DirectoryContext::sessions_dir`](crate::config_io::DirectoryContext::sessions_dir).
+pub fn get_session_path(sessions_dir: &Path, working_dir: &Path) -> PathBuf {
     let canonical = working_dir
         .canonicalize()
         .unwrap_or_else(|_| working_dir.to_path_buf());
     let filename = format!("{}.json", encode_path_for_filename(&canonical));
-    Ok(get_sessions_dir()?.join(filename))
+    sessions_dir.join(filename)
+}
+
+/// Write `content` to `path` atomically
+///
+/// The content goes to a temporary file in the same directory, is synced to
+/// disk, and is then renamed over `path`, so readers see either the old or the
+/// new file, never a partial one.
+fn write_atomic(path: &Path, content: &[u8]) -> io::Result<()> {
+    let temp_path = path.with_extension("json.tmp");
+    {
+        let mut file = std::fs::File::create(&temp_path)?;
+        file.write_all(content)?;
+        file.sync_all()?; // Ensure data is on disk before rename
+    }
+    std::fs::rename(&temp_path, path)
+}
+
+/// Get the directory holding the session snapshots for a working directory
+pub fn get_snapshot_dir(sessions_dir: &Path, working_dir: &Path) -> PathBuf {
+    let canonical = working_dir
+        .canonicalize()
+        .unwrap_or_else(|_| working_dir.to_path_buf());
+    sessions_dir
+        .join("snapshots")
+        .join(encode_path_for_filename(&canonical))
+}
+
+/// A readable session snapshot, as listed by [`Session::list_snapshots`]
+#[derive(Debug, Clone)]
+pub struct SessionSnapshot {
+    /// Snapshot file
+    pub path: PathBuf,
+    /// The snapshotted session
+    pub session: Session,
+}
+
+impl SessionSnapshot {
+    /// Files open in the snapshot (relative to its working directory)
+    pub fn files(&self) -> Vec<PathBuf> {
+        collect_session_files(&self.session)
+    }
+
+    /// Number of buffers the snapshot restores (files and terminals)
+    pub fn buffer_count(&self) -> usize {
+        self.files().len() + self.session.terminals.len()
+    }
+}
+
+/// Unique files open in a session's splits, in the order they first appear
+fn collect_session_files(session: &Session) -> Vec<PathBuf> {
+    let mut split_ids: Vec<_> = session.split_states.keys().copied().collect();
+    split_ids.sort_unstable();
+    let mut files: Vec<PathBuf> = Vec::new();
+    for split_id in split_ids {
+        let state = &session.split_states[&split_id];
+        let tab_files = state.open_tabs.iter().filter_map(|tab| match tab {
+            SerializedTabRef::File(path) => Some(path),
+            _ => None,
+        });
+        for path in tab_files.chain(state.open_files.iter()) {
+            if !files.contains(path) {
+                files.push(path.clone());
+            }
+        }
+    }
+    files
+}
+
+/// Get the file holding the named layouts for a working directory
+pub fn get_layouts_path(sessions_dir: &Path, working_dir: &Path) -> PathBuf {
+    let canonical = working_dir
+        .canonicalize()
+        .unwrap_or_else(|_| working_dir.to_path_buf());
+    sessions_dir
+        .join("layouts")
+        .join(format!("{}.json", encode_path_for_filename(&canonical)))
+}
+
+/// A split arrangement saved under a name
+///
+/// Holds the same split tree and per-split view states as a [`Session`], and
+/// the terminals and plugin panels the tree refers to by index.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NamedLayout {
+    pub name: String,
+    pub split_layout: SerializedSplitNode,
+    pub active_split_id: usize,
+    pub split_states: HashMap<usize, SerializedSplitViewState>,
+    #[serde(default)]
+    pub terminals: Vec<SerializedTerminalSession>,
+    #[serde(default)]
+    pub panels: Vec<SerializedPanel>,
+}
+
+/// The named layouts of a working directory
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct NamedLayouts {
+    /// Saved layouts, in the order they were first saved
+    #[serde(default)]
+    pub layouts: Vec<NamedLayout>,
+    /// The layout replaced by the last switch
+    #[serde(default)]
+    pub previous: Option<NamedLayout>,
+}
+
+impl NamedLayouts {
+    /// Load the named layouts for a working directory (empty if none are saved)
+    pub fn load(sessions_dir: &Path, working_dir: &Path) -> Result<Self, SessionError> {
+        let path = get_layouts_path(sessions_dir, working_dir);
+        match std::fs::read_to_string(&path) {
+            Ok(content) => Ok(serde_json::from_str(&content)?),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Save the named layouts for a working directory
+    pub fn save(&self, sessions_dir: &Path, working_dir: &Path) -> Result<(), SessionError> {
+        let path = get_layouts_path(sessions_dir, working_dir);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let content = serde_json::to_string_pretty(self)?;
+        write_atomic(&path, content.as_bytes())?;
+        Ok(())
+    }
+
+    /// Find a layout by name
+    pub fn get(&self, name: &str) -> Option<&NamedLayout> {
+        self.layouts.iter().find(|layout| layout.name == name)
+    }
+
+    /// Add a layout, replacing one with the same name in place
+    pub fn insert(&mut self, layout: NamedLayout) {
+        match self.layouts.iter_mut().find(|l| l.name == layout.name) {
+            Some(existing) => *existing = layout,
+            None => self.layouts.push(layout),
+        }
+    }
+
+    /// Remove a layout by name, returning whether it existed
+    pub fn remove(&mut self, name: &str) -> bool {
+        let before = self.layouts.len();
+        self.layouts.retain(|layout| layout.name != name);
+        self.layouts.len() != before
+    }
+}
+
+/// Get the instance marker file for a working directory
+pub fn get_instance_marker_path(sessions_dir: &Path, working_dir: &Path) -> PathBuf {
+    let canonical = working_dir
+        .canonicalize()
+        .unwrap_or_else(|_| working_dir.to_path_buf());
+    sessions_dir
+        .join("instances")
+        .join(format!("{}.json", encode_path_for_filename(&canonical)))
+}
+
+/// The editor instance that owns a working directory's session
+///
+/// Only the owner saves the session and writes recovery files, so two
+/// instances open in the same directory don't overwrite each other's state.
+/// The marker is written when an instance takes ownership and removed when
+/// it exits; a marker left by a process that is no longer running is stale.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InstanceMarker {
+    /// Process ID of the owner
+    pub pid: u32,
+    /// Tells apart editors created by the same process
+    pub instance: u64,
+    /// Unix timestamp when the owner started
+    pub started_at: u64,
+}
+
+impl InstanceMarker {
+    /// A marker for a new editor in this process
+    pub fn for_new_instance() -> Self {
+        static NEXT_INSTANCE: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(0);
+        Self {
+            pid: std::process::id(),
+            instance: NEXT_INSTANCE.fetch_add(1, std::sync::atomic::Ordering::Relaxed),
+            started_at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs(),
+        }
+    }
+
+    /// Whether the owner's process is still running
+    pub fn is_running(&self) -> bool {
+        crate::services::recovery::types::is_process_running(self.pid)
+    }
+
+    /// Read the marker of the instance owning the session of `working_dir`
+    ///
+    /// A stale or unreadable marker is removed and reads as no owner.
+    pub fn read_owner(sessions_dir: &Path, working_dir: &Path) -> Option<Self> {
+        let path = get_instance_marker_path(sessions_dir, working_dir);
+        let content = std::fs::read_to_string(&path).ok()?;
+        match serde_json::from_str::<Self>(&content) {
+            Ok(marker) if marker.is_running() => return Some(marker),
+            Ok(marker) => tracing::info!("Removing stale instance marker of pid {}", marker.pid),
+            Err(e) => tracing::warn!("Removing unreadable instance marker {:?}: {}", path, e),
+        }
+        if let Err(e) = std::fs::remove_file(&path) {
+            tracing::warn!("Failed to remove instance marker {:?}: {}", path, e);
+        }
+        None
+    }
+
+    /// Make this instance the owner of the session of `working_dir`
+    pub fn write(&self, sessions_dir: &Path, working_dir: &Path) -> io::Result<()> {
+        let path = get_instance_marker_path(sessions_dir, working_dir);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let content = serde_json::to_string_pretty(self).map_err(io::Error::other)?;
+        write_atomic(&path, content.as_bytes())
+    }
+
+    /// Remove the marker of `working_dir` if this instance is the owner
+    pub fn remove(&self, sessions_dir: &Path, working_dir: &Path) -> io::Result<()> {
+        if Self::read_owner(sessions_dir, working_dir).as_ref() != Some(self) {
+            return Ok(());
+        }
+        std::fs::remove_file(get_instance_marker_path(sessions_dir, working_dir))
+    }
+}
+
+/// Where the previous session file is kept when a new one is saved over it
+///
+/// A file written by a newer version of the editor (`version` above
+/// [`SESSION_VERSION`]) is kept as `{name}.v{version}.json.bak` instead, so
+/// the next save doesn't replace it and going back to that version can still
+/// restore it.
+pub fn get_session_backup_path(session_path: &Path, version: u32) -> PathBuf {
+    if version > SESSION_VERSION {
+        session_path.with_extension(format!("v{}.json.bak", version))
+    } else {
+        session_path.with_extension("json.bak")
+    }
+}
+
+/// Format version of a session file's JSON
+///
+/// Files without one predate the field and count as version 1.
+fn session_version(session: &Value) -> u32 {
+    session
+        .get("version")
+        .and_then(Value::as_u64)
+        .map_or(1, |version| u32::try_from(version).unwrap_or(u32::MAX))
+}
+
+/// Bring a session file's JSON up to [`SESSION_VERSION`]
+///
+/// Returns the version the file was written with.
+fn migrate_session(session: &mut Value) -> Result<u32, SessionError> {
+    let version = session_version(session);
+    if version > SESSION_VERSION {
+        return Err(SessionError::VersionTooNew {
+            version,
+            max_supported: SESSION_VERSION,
+        });
+    }
+    for (from, upgrade) in MIGRATIONS {
+        if *from >= version {
+            tracing::debug!("Upgrading session from version {}", from);
+            upgrade(session);
+        }
+    }
+    if let Some(fields) = session.as_object_mut() {
+        fields.insert("version".to_string(), SESSION_VERSION.into());
+    }
+    Ok(version)
+}
+
+/// Version 2 reads tabs from `open_tabs` only
+///
+/// Splits saved before terminal tabs existed only list `open_files` and
+/// `active_file_index`; they become file tabs.
+fn migrate_v1_to_v2(session: &mut Value) {
+    let Some(split_states) = session
+        .get_mut("split_states")
+        .and_then(Value::as_object_mut)
+    else {
+        return;
+    };
+    for state in split_states.values_mut() {
+        let Some(state) = state.as_object_mut() else {
+            continue;
+        };
+        let has_tabs = state
+            .get("open_tabs")
+            .and_then(Value::as_array)
+            .is_some_and(|tabs| !tabs.is_empty());
+        let files = state
+            .get("open_files")
+            .and_then(Value::as_array)
+            .cloned()
+            .unwrap_or_default();
+        if has_tabs || files.is_empty() {
+            continue;
+        }
+        let active = state
+            .get("active_file_index")
+            .and_then(Value::as_u64)
+            .filter(|&index| (index as usize) < files.len());
+        let tabs = files
+            .into_iter()
+            .map(|path| serde_json::json!({ "File": path }))
+            .collect();
+        state.insert("open_tabs".to_string(), Value::Array(tabs));
+        state.insert("active_tab_index".to_string(), active.into());
+    }
 }
 
 /// Session error types
@@ -591,8 +1085,8 @@ impl From<serde_json::Error> for SessionError {
 
 impl Session {
     /// Load session for a working directory (if exists)
-    pub fn load(working_dir: &Path) -> Result<Option<Session>, SessionError> {
-        let path = get_session_path(working_dir)?;
+    pub fn load(sessions_dir: &Path, working_dir: &Path) -> Result<Option<Session>, SessionError> {
+        let path = get_session_path(sessions_dir, working_dir);
         tracing::debug!("Looking for session at {:?}", path);
 
         if !path.exists() {
@@ -600,16 +1094,30 @@ impl Session {
             return Ok(None);
         }
 
-        tracing::debug!("Loading session from {:?}", path);
-        let content = std::fs::read_to_string(&path)?;
-        let session: Session = serde_json::from_str(&content)?;
+        Self::load_from_path(&path, working_dir).map(Some)
+    }
+
+    /// Read a session from the JSON of a session file, upgrading older versions
+    pub fn from_json(content: &str) -> Result<Session, SessionError> {
+        let mut value: Value = serde_json::from_str(content)?;
+        let version = migrate_session(&mut value)?;
+        let session: Session = serde_json::from_value(value)?;
 
         tracing::debug!(
-            "Loaded session: version={}, split_states={}, active_split={}",
+            "Loaded session: version={} (written as {}), split_states={}, active_split={}",
             session.version,
+            version,
             session.split_states.len(),
             session.active_split_id
         );
+        Ok(session)
+    }
+
+    /// Load and validate a session (or snapshot) file for a working directory
+    pub fn load_from_path(path: &Path, working_dir: &Path) -> Result<Session, SessionError> {
+        tracing::debug!("Loading session from {:?}", path);
+        let content = std::fs::read_to_string(path)?;
+        let session = Self::from_json(&content)?;
 
         // Validate working_dir matches (canonicalize both for comparison)
         let expected = working_dir
@@ -629,20 +1137,21 @@ impl Session {
             return Err(SessionError::WorkdirMismatch { expected, found });
         }
 
-        // Check version compatibility
-        if session.version > SESSION_VERSION {
-            tracing::warn!(
-                "Session version {} is newer than supported {}",
-                session.version,
-                SESSION_VERSION
-            );
-            return Err(SessionError::VersionTooNew {
-                version: session.version,
-                max_supported: SESSION_VERSION,
-            });
-        }
+        Ok(session)
+    }
 
-        Ok(Some(session))
+    /// Load the backup of the previous session file for a working directory
+    pub fn load_backup(sessions_dir: &Path, working_dir: &Path) -> Option<Session> {
+        let path = get_session_backup_path(
+            &get_session_path(sessions_dir, working_dir),
+            SESSION_VERSION,
+        );
+        if !path.exists() {
+            return None;
+        }
+        Self::load_from_path(&path, working_dir)
+            .inspect_err(|e| tracing::debug!("Session backup {:?} is unreadable: {}", path, e))
+            .ok()
     }
 
     /// Save session to file using atomic write (temp file + rename)
@@ -651,8 +1160,8 @@ impl Session {
     /// 1. Write to a temporary file in the same directory
     /// 2. Sync to disk (fsync)
     /// 3. Atomically rename to the final path
-    pub fn save(&self) -> Result<(), SessionError> {
-        let path = get_session_path(&self.working_dir)?;
+    pub fn save(&self, sessions_dir: &Path) -> Result<(), SessionError> {
+        let path = get_session_path(sessions_dir, &self.working_dir);
         tracing::debug!("Saving session to {:?}", path);
 
         // Ensure directory exists
@@ -664,26 +1173,157 @@ impl Session {
         let content = serde_json::to_string_pretty(self)?;
         tracing::trace!("Session JSON size: {} bytes", content.len());
 
-        // Write atomically: temp file + rename
-        let temp_path = path.with_extension("json.tmp");
+        Self::back_up(&path);
+        write_atomic(&path, content.as_bytes())?;
+        tracing::info!("Session saved to {:?}", path);
+
+        Ok(())
+    }
 
-        // Write to temp file
+    /// Save the session unless the session file already holds it
+    ///
+    /// Sessions differing only in `saved_at` count as the same. Returns
+    /// whether the file was written.
+    pub fn save_if_changed(&self, sessions_dir: &Path) -> Result<bool, SessionError> {
+        let path = get_session_path(sessions_dir, &self.working_dir);
+        if let Some(mut saved) = std::fs::read(&path)
+            .ok()
+            .and_then(|content| serde_json::from_slice::<Value>(&content).ok())
         {
-            let mut file = std::fs::File::create(&temp_path)?;
-            file.write_all(content.as_bytes())?;
-            file.sync_all()?; // Ensure data is on disk before rename
+            // Through text, like the saved file, so floats compare equal
+            let mut current: Value = serde_json::from_str(&serde_json::to_string(self)?)?;
+            for value in [&mut saved, &mut current] {
+                if let Some(object) = value.as_object_mut() {
+                    object.remove("saved_at");
+                }
+            }
+            if saved == current {
+                tracing::debug!("Session unchanged, not saving {:?}", path);
+                return Ok(false);
+            }
         }
+        self.save(sessions_dir)?;
+        Ok(true)
+    }
 
-        // Atomic rename
-        std::fs::rename(&temp_path, &path)?;
-        tracing::info!("Session saved to {:?}", path);
+    /// Copy the session file at `path`, if any, to its backup path
+    ///
+    /// Failing to back up is logged and doesn't stop the save.
+    fn back_up(path: &Path) {
+        let Ok(previous) = std::fs::read(path) else {
+            return;
+        };
+        // An unreadable file is still worth keeping; it counts as current
+        let version = serde_json::from_slice::<Value>(&previous)
+            .map_or(SESSION_VERSION, |value| session_version(&value));
+        let backup = get_session_backup_path(path, version);
+        if let Err(e) = write_atomic(&backup, &previous) {
+            tracing::warn!("Failed to back up session file to {:?}: {}", backup, e);
+        }
+    }
 
+    /// Save a timestamped snapshot of the session, then prune old snapshots
+    ///
+    /// Keeps at most `keep` snapshots whose total size stays within
+    /// `max_bytes`, dropping the oldest first; the snapshot just written is
+    /// always kept.
+    pub fn save_snapshot(
+        &self,
+        sessions_dir: &Path,
+        keep: usize,
+        max_bytes: usize,
+    ) -> Result<PathBuf, SessionError> {
+        let dir = get_snapshot_dir(sessions_dir, &self.working_dir);
+        std::fs::create_dir_all(&dir)?;
+
+        let millis = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis();
+        // Zero-padded so that names sort in time order
+        let path = dir.join(format!("{:015}.json", millis));
+        let content = serde_json::to_string_pretty(self)?;
+        write_atomic(&path, content.as_bytes())?;
+        tracing::debug!("Session snapshot saved to {:?}", path);
+
+        Self::prune_snapshots(&dir, keep.max(1), max_bytes)?;
+        Ok(path)
+    }
+
+    /// Snapshot files in a snapshot directory, newest first
+    fn snapshot_files(dir: &Path) -> io::Result<Vec<PathBuf>> {
+        let mut files: Vec<PathBuf> = match std::fs::read_dir(dir) {
+            Ok(entries) => entries
+                .filter_map(|entry| entry.ok().map(|e| e.path()))
+                .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+                .collect(),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => return Err(e),
+        };
+        files.sort_unstable_by(|a, b| b.cmp(a));
+        Ok(files)
+    }
+
+    /// Remove the snapshots beyond the newest `keep`, or beyond `max_bytes` in total
+    fn prune_snapshots(dir: &Path, keep: usize, max_bytes: usize) -> io::Result<()> {
+        let mut kept = 0;
+        let mut total_bytes = 0u64;
+        for path in Self::snapshot_files(dir)? {
+            let size = std::fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+            let fits = kept == 0 || (kept < keep && total_bytes + size <= max_bytes as u64);
+            if fits {
+                kept += 1;
+                total_bytes += size;
+            } else {
+                tracing::debug!("Pruning session snapshot {:?}", path);
+                std::fs::remove_file(&path)?;
+                // Once one is dropped, all older ones go too
+                kept = keep;
+            }
+        }
         Ok(())
     }
 
+    /// List the readable snapshots for a working directory, newest first
+    ///
+    /// Snapshots that fail to load are skipped.
+    pub fn list_snapshots(
+        sessions_dir: &Path,
+        working_dir: &Path,
+    ) -> Result<Vec<SessionSnapshot>, SessionError> {
+        let dir = get_snapshot_dir(sessions_dir, working_dir);
+        let snapshots = Self::snapshot_files(&dir)?
+            .into_iter()
+            .filter_map(|path| match Self::load_from_path(&path, working_dir) {
+                Ok(session) => Some(SessionSnapshot { path, session }),
+                Err(e) => {
+                    tracing::warn!("Skipping unreadable session snapshot {:?}: {}", path, e);
+                    None
+                }
+            })
+            .collect();
+        Ok(snapshots)
+    }
+
+    /// Load the newest readable snapshot for a working directory
+    pub fn load_newest_snapshot(
+        sessions_dir: &Path,
+        working_dir: &Path,
+    ) -> Option<SessionSnapshot> {
+        let dir = get_snapshot_dir(sessions_dir, working_dir);
+        Self::snapshot_files(&dir)
+            .ok()?
+            .into_iter()
+            .find_map(|path| {
+                Self::load_from_path(&path, working_dir)
+                    .ok()
+                    .map(|session| SessionSnapshot { path, session })
+            })
+    }
+
     /// Delete session for a working directory
-    pub fn delete(working_dir: &Path) -> Result<(), SessionError> {
-        let path = get_session_path(working_dir)?;
+    pub fn delete(sessions_dir: &Path, working_dir: &Path) -> Result<(), SessionError> {
+        let path = get_session_path(sessions_dir, working_dir);
         if path.exists() {
             std::fs::remove_file(path)?;
         }
@@ -707,6 +1347,9 @@ impl Session {
             search_options: SearchOptions::default(),
             bookmarks: HashMap::new(),
             terminals: Vec::new(),
+            panels: Vec::new(),
+            pinned_files: Vec::new(),
+            distraction_free: false,
             saved_at: SystemTime::now()
                 .duration_since(UNIX_EPOCH)
                 .unwrap_or_default()
@@ -739,18 +1382,20 @@ mod tests {
         assert_eq!(decoded, PathBuf::from("/home/user/project"));
 
         // Different paths should give different encodings
-        let path1 = get_session_path(Path::new("/home/user/project")).unwrap();
-        let path2 = get_session_path(Path::new("/home/user/other")).unwrap();
+        let sessions_dir = Path::new("/state/fresh/sessions");
+        let path1 = get_session_path(sessions_dir, Path::new("/home/user/project"));
+        let path2 = get_session_path(sessions_dir, Path::new("/home/user/other"));
         assert_ne!(path1, path2);
 
         // Same path should give same encoding
-        let path1_again = get_session_path(Path::new("/home/user/project")).unwrap();
+        let path1_again = get_session_path(sessions_dir, Path::new("/home/user/project"));
         assert_eq!(path1, path1_again);
 
         // Filename should end with .json and be readable
         let filename = path1.file_name().unwrap().to_str().unwrap();
         assert!(filename.ends_with(".json"));
         assert!(filename.starts_with("home_user_project"));
+        assert!(path1.starts_with(sessions_dir));
     }
 
     #[test]
@@ -843,6 +1488,52 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_panel_serialization() {
+        let mut session = Session::new(PathBuf::from("/home/user/test"));
+        session.split_layout = SerializedSplitNode::Split {
+            direction: SerializedSplitDirection::Horizontal,
+            first: Box::new(SerializedSplitNode::Leaf {
+                file_path: Some(PathBuf::from("src/main.rs")),
+                split_id: 1,
+            }),
+            second: Box::new(SerializedSplitNode::Panel {
+                panel_index: 0,
+                split_id: 2,
+            }),
+            ratio: 0.7,
+            split_id: 0,
+        };
+        session.panels.push(SerializedPanel {
+            panel_index: 0,
+            plugin: "diagnostics_panel".to_string(),
+            panel_id: "diagnostics".to_string(),
+            name: "*Diagnostics*".to_string(),
+            mode: "diagnostics-list".to_string(),
+            pinned: false,
+        });
+
+        let json = serde_json::to_string(&session).unwrap();
+        let restored: Session = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(restored.panels.len(), 1);
+        assert_eq!(restored.panels[0].plugin, "diagnostics_panel");
+        assert_eq!(restored.panels[0].panel_id, "diagnostics");
+        match restored.split_layout {
+            SerializedSplitNode::Split { second, ratio, .. } => {
+                assert_eq!(ratio, 0.7);
+                assert!(matches!(
+                    *second,
+                    SerializedSplitNode::Panel {
+                        panel_index: 0,
+                        split_id: 2
+                    }
+                ));
+            }
+            _ => panic!("Expected Split node"),
+        }
+    }
+
     #[test]
     fn test_file_state_serialization() {
         let file_state = SerializedFileState {
@@ -956,6 +1647,8 @@ mod tests {
                 tab_scroll_offset: 0,
                 view_mode: SerializedViewMode::Source,
                 compose_width: None,
+                input_locked: false,
+                search: None,
             },
         );
 
@@ -1084,4 +1777,379 @@ mod tests {
         assert_eq!(restored.expanded_dirs.len(), 3);
         assert_eq!(restored.scroll_offset, 5);
     }
+
+    /// Save a snapshot of `session`, waiting so that snapshot names differ
+    fn save_snapshot_later(session: &Session, sessions_dir: &Path, keep: usize, max_bytes: usize) {
+        std::thread::sleep(std::time::Duration::from_millis(2));
+        session
+            .save_snapshot(sessions_dir, keep, max_bytes)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_snapshot_rotation_keeps_newest() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let sessions_dir = temp_dir.path().join("sessions");
+        let working_dir = temp_dir.path().to_path_buf();
+
+        let mut session = Session::new(working_dir.clone());
+        for index in 0..5 {
+            session.saved_at = index;
+            save_snapshot_later(&session, &sessions_dir, 3, usize::MAX);
+        }
+
+        let snapshots = Session::list_snapshots(&sessions_dir, &working_dir).unwrap();
+        let saved_at: Vec<u64> = snapshots.iter().map(|s| s.session.saved_at).collect();
+        assert_eq!(saved_at, vec![4, 3, 2]);
+    }
+
+    #[test]
+    fn test_snapshot_rotation_bounded_by_size() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let sessions_dir = temp_dir.path().join("sessions");
+        let working_dir = temp_dir.path().to_path_buf();
+
+        let mut session = Session::new(working_dir.clone());
+        let size = serde_json::to_string_pretty(&session).unwrap().len();
+        for index in 0..4 {
+            session.saved_at = index;
+            save_snapshot_later(&session, &sessions_dir, 10, size * 2);
+        }
+        let snapshots = Session::list_snapshots(&sessions_dir, &working_dir).unwrap();
+        assert_eq!(snapshots.len(), 2);
+        assert_eq!(snapshots[0].session.saved_at, 3);
+
+        // The newest snapshot is kept even when it alone is over the limit
+        session.saved_at = 4;
+        save_snapshot_later(&session, &sessions_dir, 10, 1);
+        let snapshots = Session::list_snapshots(&sessions_dir, &working_dir).unwrap();
+        assert_eq!(snapshots.len(), 1);
+        assert_eq!(snapshots[0].session.saved_at, 4);
+    }
+
+    #[test]
+    fn test_unreadable_snapshots_are_skipped() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let sessions_dir = temp_dir.path().join("sessions");
+        let working_dir = temp_dir.path().to_path_buf();
+
+        let mut session = Session::new(working_dir.clone());
+        session.saved_at = 1;
+        save_snapshot_later(&session, &sessions_dir, 10, usize::MAX);
+        session.saved_at = 2;
+        save_snapshot_later(&session, &sessions_dir, 10, usize::MAX);
+
+        let newest = Session::list_snapshots(&sessions_dir, &working_dir).unwrap()[0]
+            .path
+            .clone();
+        std::fs::write(&newest, "{ truncated").unwrap();
+
+        let snapshots = Session::list_snapshots(&sessions_dir, &working_dir).unwrap();
+        assert_eq!(snapshots.len(), 1);
+        let fallback = Session::load_newest_snapshot(&sessions_dir, &working_dir).unwrap();
+        assert_eq!(fallback.session.saved_at, 1);
+    }
+
+    #[test]
+    fn test_v1_open_files_fixture() {
+        let session = Session::from_json(include_str!(
+            "../tests/fixtures/sessions/v1_open_files.json"
+        ))
+        .unwrap();
+        assert_eq!(session.version, SESSION_VERSION);
+        assert_eq!(session.working_dir, PathBuf::from("/home/user/project"));
+
+        let split_state = &session.split_states[&0];
+        let tabs: Vec<_> = split_state
+            .open_tabs
+            .iter()
+            .map(|tab| match tab {
+                SerializedTabRef::File(path) => path.clone(),
+                other => panic!("unexpected tab {:?}", other),
+            })
+            .collect();
+        assert_eq!(
+            tabs,
+            vec![PathBuf::from("README.md"), PathBuf::from("src/main.rs")]
+        );
+        assert_eq!(split_state.active_tab_index, Some(1));
+        let file_state = &split_state.file_states[Path::new("src/main.rs")];
+        assert_eq!(file_state.cursor.position, 120);
+        assert_eq!(file_state.scroll.top_byte, 64);
+        assert!(session.file_explorer.visible);
+        assert!(session.terminals.is_empty());
+        assert!(session.histories.search.is_empty());
+    }
+
+    #[test]
+    fn test_v1_open_tabs_fixture() {
+        let session =
+            Session::from_json(include_str!("../tests/fixtures/sessions/v1_open_tabs.json"))
+                .unwrap();
+        assert_eq!(session.version, SESSION_VERSION);
+        assert!(matches!(
+            session.split_layout,
+            SerializedSplitNode::Split { split_id: 0, .. }
+        ));
+        assert_eq!(session.split_states[&1].open_tabs.len(), 2);
+        assert!(matches!(
+            session.split_states[&2].open_tabs[..],
+            [SerializedTabRef::Terminal(0)]
+        ));
+        assert_eq!(session.terminals.len(), 1);
+        assert_eq!(session.bookmarks[&'a'].position, 300);
+        assert_eq!(session.histories.goto_line, vec!["42".to_string()]);
+        assert!(session.search_options.case_sensitive);
+        assert!(session.panels.is_empty());
+    }
+
+    #[test]
+    fn test_migrate_v1_to_v2() {
+        let mut value = serde_json::json!({
+            "split_states": {
+                "0": { "open_files": ["a.rs", "b.rs"], "active_file_index": 5 },
+                "1": {
+                    "open_tabs": [{ "Terminal": 0 }],
+                    "active_tab_index": 0,
+                    "open_files": ["c.rs"]
+                },
+                "2": {}
+            }
+        });
+        migrate_v1_to_v2(&mut value);
+
+        let states = &value["split_states"];
+        assert_eq!(
+            states["0"]["open_tabs"],
+            serde_json::json!([{ "File": "a.rs" }, { "File": "b.rs" }])
+        );
+        // An out-of-range index leaves no tab active
+        assert_eq!(states["0"]["active_tab_index"], Value::Null);
+        // Splits that already list their tabs are left alone
+        assert_eq!(
+            states["1"]["open_tabs"],
+            serde_json::json!([{ "Terminal": 0 }])
+        );
+        assert_eq!(states["2"], serde_json::json!({}));
+    }
+
+    #[test]
+    fn test_missing_version_is_version_1() {
+        let mut value: Value = serde_json::from_str(include_str!(
+            "../tests/fixtures/sessions/v1_open_files.json"
+        ))
+        .unwrap();
+        value.as_object_mut().unwrap().remove("version");
+        assert_eq!(session_version(&value), 1);
+        assert_eq!(migrate_session(&mut value).unwrap(), 1);
+        assert_eq!(value["version"], SESSION_VERSION);
+        assert!(value["split_states"]["0"]["open_tabs"].is_array());
+    }
+
+    #[test]
+    fn test_newer_version_is_refused_before_parsing() {
+        // The shape is unknown to this version, so only the version is read
+        let json = format!(
+            r#"{{"version": {}, "layout": {{"tiles": []}}}}"#,
+            SESSION_VERSION + 1
+        );
+        match Session::from_json(&json) {
+            Err(SessionError::VersionTooNew {
+                version,
+                max_supported,
+            }) => {
+                assert_eq!(version, SESSION_VERSION + 1);
+                assert_eq!(max_supported, SESSION_VERSION);
+            }
+            other => panic!("expected VersionTooNew, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_corrupted_session_is_an_error() {
+        for content in ["", "{ truncated", "[]", r#"{"version": "two"}"#, "null"] {
+            assert!(
+                matches!(Session::from_json(content), Err(SessionError::Json(_))),
+                "{:?} should fail to parse",
+                content
+            );
+        }
+    }
+
+    #[test]
+    fn test_save_backs_up_previous_session() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let sessions_dir = temp_dir.path().join("sessions");
+        let working_dir = temp_dir.path().to_path_buf();
+        let path = get_session_path(&sessions_dir, &working_dir);
+
+        let mut session = Session::new(working_dir.clone());
+        session.saved_at = 1;
+        session.save(&sessions_dir).unwrap();
+        assert!(Session::load_backup(&sessions_dir, &working_dir).is_none());
+
+        session.saved_at = 2;
+        session.save(&sessions_dir).unwrap();
+        let backup = Session::load_backup(&sessions_dir, &working_dir).unwrap();
+        assert_eq!(backup.saved_at, 1);
+
+        // A file from a newer version is kept under its own name
+        let newer = format!(r#"{{"version": {}}}"#, SESSION_VERSION + 1);
+        std::fs::write(&path, &newer).unwrap();
+        session.save(&sessions_dir).unwrap();
+        let kept = get_session_backup_path(&path, SESSION_VERSION + 1);
+        assert_eq!(std::fs::read_to_string(kept).unwrap(), newer);
+        assert_eq!(
+            Session::load_backup(&sessions_dir, &working_dir)
+                .unwrap()
+                .saved_at,
+            1
+        );
+    }
+
+    #[test]
+    fn test_session_write_skips_unchanged_sections() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let sessions_dir = temp_dir.path().join("sessions");
+        let states_dir = temp_dir.path().join("file_states");
+        let working_dir = temp_dir.path().to_path_buf();
+        let file_state = |position| SerializedFileState {
+            cursor: SerializedCursor {
+                position,
+                anchor: None,
+                sticky_column: 0,
+            },
+            additional_cursors: Vec::new(),
+            scroll: SerializedScroll {
+                top_byte: 0,
+                top_view_line_offset: 0,
+                left_column: 0,
+            },
+        };
+        let mut write = SessionWrite {
+            session: Session::new(working_dir.clone()),
+            file_states: vec![
+                (working_dir.join("a.txt"), file_state(1)),
+                (working_dir.join("b.txt"), file_state(2)),
+            ],
+            snapshots: 5,
+            snapshots_max_bytes: usize::MAX,
+        };
+        let written = write.write(&sessions_dir, &states_dir).unwrap();
+        assert_eq!(
+            written,
+            SessionWriteSummary {
+                session_written: true,
+                file_states_written: 2,
+            }
+        );
+
+        // Saving again later with nothing changed writes nothing
+        write.session.saved_at += 60;
+        let written = write.write(&sessions_dir, &states_dir).unwrap();
+        assert_eq!(written, SessionWriteSummary::default());
+
+        // Only what changed is written
+        write.file_states[1].1 = file_state(7);
+        let written = write.write(&sessions_dir, &states_dir).unwrap();
+        assert_eq!(
+            written,
+            SessionWriteSummary {
+                session_written: false,
+                file_states_written: 1,
+            }
+        );
+        assert_eq!(
+            PersistedFileSession::load(&states_dir, &working_dir.join("b.txt"))
+                .unwrap()
+                .cursor
+                .position,
+            7
+        );
+
+        // Snapshot names have millisecond resolution
+        std::thread::sleep(std::time::Duration::from_millis(2));
+        write.session.distraction_free = true;
+        let written = write.write(&sessions_dir, &states_dir).unwrap();
+        assert!(written.session_written);
+        let snapshots = Session::list_snapshots(&sessions_dir, &working_dir).unwrap();
+        assert_eq!(snapshots.len(), 2);
+    }
+
+    #[test]
+    fn test_instance_marker_ownership() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let sessions_dir = temp_dir.path();
+        let working_dir = Path::new("/home/user/project");
+        assert_eq!(InstanceMarker::read_owner(sessions_dir, working_dir), None);
+
+        let first = InstanceMarker::for_new_instance();
+        let second = InstanceMarker::for_new_instance();
+        assert_ne!(first, second);
+        first.write(sessions_dir, working_dir).unwrap();
+        assert_eq!(
+            InstanceMarker::read_owner(sessions_dir, working_dir),
+            Some(first.clone())
+        );
+
+        // Only the owner's marker is removed
+        second.remove(sessions_dir, working_dir).unwrap();
+        assert_eq!(
+            InstanceMarker::read_owner(sessions_dir, working_dir),
+            Some(first.clone())
+        );
+        first.remove(sessions_dir, working_dir).unwrap();
+        assert_eq!(InstanceMarker::read_owner(sessions_dir, working_dir), None);
+    }
+
+    #[test]
+    fn test_stale_instance_marker_is_removed() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let sessions_dir = temp_dir.path();
+        let working_dir = Path::new("/home/user/project");
+        let stale = InstanceMarker {
+            pid: u32::MAX / 2,
+            ..InstanceMarker::for_new_instance()
+        };
+        stale.write(sessions_dir, working_dir).unwrap();
+
+        assert_eq!(InstanceMarker::read_owner(sessions_dir, working_dir), None);
+        assert!(!get_instance_marker_path(sessions_dir, working_dir).exists());
+    }
+
+    #[test]
+    fn test_named_layouts_keep_order_and_replace_by_name() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let sessions_dir = temp_dir.path().join("sessions");
+        let working_dir = temp_dir.path().to_path_buf();
+        let layout = |name: &str, file: &str| NamedLayout {
+            name: name.to_string(),
+            split_layout: SerializedSplitNode::Leaf {
+                file_path: Some(PathBuf::from(file)),
+                split_id: 1,
+            },
+            active_split_id: 1,
+            split_states: HashMap::new(),
+            terminals: Vec::new(),
+            panels: Vec::new(),
+        };
+
+        let mut layouts = NamedLayouts::load(&sessions_dir, &working_dir).unwrap();
+        assert!(layouts.layouts.is_empty());
+        layouts.insert(layout("coding", "a.rs"));
+        layouts.insert(layout("review", "b.rs"));
+        layouts.insert(layout("coding", "c.rs"));
+        layouts.save(&sessions_dir, &working_dir).unwrap();
+
+        let mut loaded = NamedLayouts::load(&sessions_dir, &working_dir).unwrap();
+        let names: Vec<&str> = loaded.layouts.iter().map(|l| l.name.as_str()).collect();
+        assert_eq!(names, ["coding", "review"]);
+        assert!(matches!(
+            &loaded.get("coding").unwrap().split_layout,
+            SerializedSplitNode::Leaf { file_path: Some(path), .. } if path == Path::new("c.rs")
+        ));
+        assert!(loaded.remove("coding"));
+        assert!(!loaded.remove("coding"));
+        assert!(loaded.get("coding").is_none());
+    }
 }
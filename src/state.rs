@@ -1,27 +1,34 @@
+use crate::input::selection_expansion::ExpansionHistory;
 use crate::model::buffer::{Buffer, LineNumber};
 use crate::model::cursor::{Cursor, Cursors};
 use crate::model::document_model::{
     DocumentCapabilities, DocumentModel, DocumentPosition, ViewportContent, ViewportLine,
 };
+use crate::model::edit_batch::EditBatch;
 use crate::model::event::{
     Event, MarginContentData, MarginPositionData, OverlayFace as EventOverlayFace, PopupData,
     PopupPositionData,
 };
 use crate::model::marker::MarkerList;
+use crate::primitives::data_path::OutlineCache;
 use crate::primitives::grammar_registry::GrammarRegistry;
 use crate::primitives::highlight_engine::HighlightEngine;
 use crate::primitives::highlighter::Language;
 use crate::primitives::indent::IndentCalculator;
+use crate::primitives::indent_rules::IndentRules;
+use crate::primitives::selection_stats::SelectionSummaryCache;
 use crate::primitives::semantic_highlight::SemanticHighlighter;
 use crate::primitives::text_property::TextPropertyManager;
 use crate::view::margin::{MarginAnnotation, MarginContent, MarginManager, MarginPosition};
 use crate::view::overlay::{Overlay, OverlayFace, OverlayManager, UnderlineStyle};
 use crate::view::popup::{Popup, PopupContent, PopupListItem, PopupManager, PopupPosition};
 use crate::view::semantic_highlight_cache::SemanticHighlightCache;
+use crate::view::theme::ThemedStyle;
 use crate::view::virtual_text::VirtualTextManager;
 use anyhow::Result;
 use ratatui::style::{Color, Style};
 use std::cell::RefCell;
+use std::sync::Arc;
 
 /// Display mode for a buffer
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -50,6 +57,10 @@ pub struct EditorState {
     /// Auto-indent calculator for smart indentation (RefCell for interior mutability)
     pub indent_calculator: RefCell<IndentCalculator>,
 
+    /// Rule-based auto-indent from the language config
+    /// When None, the indent calculator above is used.
+    pub indent_rules: Option<Arc<IndentRules>>,
+
     /// Overlays for visual decorations (underlines, highlights, etc.)
     pub overlays: OverlayManager,
 
@@ -97,6 +108,9 @@ pub struct EditorState {
     /// Used for visual display of tab characters and indent calculations.
     pub tab_size: usize,
 
+    /// Ruler columns from the language config, replacing `ui.rulers`
+    pub rulers: Option<Vec<u16>>,
+
     /// Semantic highlighter for word occurrence highlighting
     pub semantic_highlighter: SemanticHighlighter,
 
@@ -121,6 +135,16 @@ pub struct EditorState {
 
     /// Debounced semantic highlight cache
     pub semantic_highlight_cache: SemanticHighlightCache,
+
+    /// Steps taken by expand_selection for each cursor, so shrink_selection
+    /// can retrace them
+    pub selection_expansions: ExpansionHistory,
+
+    /// Key paths of JSON, YAML and TOML buffers, parsed when first needed
+    pub data_outline: OutlineCache,
+
+    /// Counts of the selection shown in the status bar
+    pub selection_summary: SelectionSummaryCache,
 }
 
 impl EditorState {
@@ -134,6 +158,7 @@ impl EditorState {
             cursors: Cursors::new(),
             highlighter: HighlightEngine::None, // No file path, so no syntax highlighting
             indent_calculator: RefCell::new(IndentCalculator::new()),
+            indent_rules: None,
             overlays: OverlayManager::new(),
             marker_list: MarkerList::new(),
             virtual_texts: VirtualTextManager::new(),
@@ -147,6 +172,7 @@ impl EditorState {
             show_whitespace_tabs: true,
             use_tabs: false,
             tab_size: 4, // Default tab size
+            rulers: None,
             semantic_highlighter: SemanticHighlighter::new(),
             view_mode: ViewMode::Source,
             debug_highlight_mode: false,
@@ -155,6 +181,9 @@ impl EditorState {
             compose_column_guides: None,
             view_transform: None,
             semantic_highlight_cache: SemanticHighlightCache::new(),
+            selection_expansions: ExpansionHistory::new(),
+            data_outline: OutlineCache::default(),
+            selection_summary: SelectionSummaryCache::default(),
         }
     }
 
@@ -216,6 +245,7 @@ impl EditorState {
             cursors: Cursors::new(),
             highlighter,
             indent_calculator: RefCell::new(IndentCalculator::new()),
+            indent_rules: None,
             overlays: OverlayManager::new(),
             marker_list,
             virtual_texts: VirtualTextManager::new(),
@@ -229,6 +259,7 @@ impl EditorState {
             show_whitespace_tabs: true,
             use_tabs: false,
             tab_size: 4, // Default tab size
+            rulers: None,
             semantic_highlighter,
             view_mode: ViewMode::Source,
             debug_highlight_mode: false,
@@ -237,6 +268,9 @@ impl EditorState {
             compose_column_guides: None,
             view_transform: None,
             semantic_highlight_cache: SemanticHighlightCache::new(),
+            selection_expansions: ExpansionHistory::new(),
+            data_outline: OutlineCache::default(),
+            selection_summary: SelectionSummaryCache::default(),
         })
     }
 
@@ -256,7 +290,21 @@ impl EditorState {
         languages: &std::collections::HashMap<String, crate::config::LanguageConfig>,
     ) -> std::io::Result<Self> {
         let buffer = Buffer::load_from_file(path, large_file_threshold)?;
+        Ok(Self::from_buffer_with_languages(
+            buffer, path, registry, languages,
+        ))
+    }
 
+    /// Create an editor state for a file whose buffer is already loaded
+    ///
+    /// Syntax detection uses `path` and the configured languages, as in
+    /// [`Self::from_file_with_languages`].
+    pub fn from_buffer_with_languages(
+        buffer: Buffer,
+        path: &std::path::Path,
+        registry: &GrammarRegistry,
+        languages: &std::collections::HashMap<String, crate::config::LanguageConfig>,
+    ) -> Self {
         // Create highlighter using HighlightEngine with language config
         let highlighter = HighlightEngine::for_file_with_languages(path, registry, languages);
         tracing::debug!(
@@ -282,11 +330,12 @@ impl EditorState {
             marker_list.adjust_for_insert(0, buffer.len());
         }
 
-        Ok(Self {
+        Self {
             buffer,
             cursors: Cursors::new(),
             highlighter,
             indent_calculator: RefCell::new(IndentCalculator::new()),
+            indent_rules: None,
             overlays: OverlayManager::new(),
             marker_list,
             virtual_texts: VirtualTextManager::new(),
@@ -300,6 +349,7 @@ impl EditorState {
             show_whitespace_tabs: true,
             use_tabs: false,
             tab_size: 4, // Default tab size
+            rulers: None,
             semantic_highlighter,
             view_mode: ViewMode::Source,
             debug_highlight_mode: false,
@@ -308,7 +358,10 @@ impl EditorState {
             compose_column_guides: None,
             view_transform: None,
             semantic_highlight_cache: SemanticHighlightCache::new(),
-        })
+            selection_expansions: ExpansionHistory::new(),
+            data_outline: OutlineCache::default(),
+            selection_summary: SelectionSummaryCache::default(),
+        }
     }
 
     /// Handle an Insert event - adjusts markers, buffer, highlighter, cursors, and line numbers
@@ -338,6 +391,8 @@ impl EditorState {
         if let Some(cursor) = self.cursors.get_mut(cursor_id) {
             cursor.position = position + text.len();
             cursor.clear_selection();
+            // Editing resets the goal column so vertical moves start from here
+            cursor.sticky_column = 0;
         }
 
         // Update primary cursor line number if this was the primary cursor
@@ -382,6 +437,8 @@ impl EditorState {
         if let Some(cursor) = self.cursors.get_mut(cursor_id) {
             cursor.position = range.start;
             cursor.clear_selection();
+            // Editing resets the goal column so vertical moves start from here
+            cursor.sticky_column = 0;
         }
 
         // Update primary cursor line number if this was the primary cursor
@@ -401,6 +458,48 @@ impl EditorState {
         }
     }
 
+    /// Apply the edits of many cursors at once - adjusts markers, buffer,
+    /// highlighter and cursors in one pass each, rather than once per edit
+    ///
+    /// Cursors that end up on top of each other are merged. Recorded as a
+    /// `BulkEdit`, which is how the edit is undone and redone.
+    pub fn apply_edit_batch(&mut self, batch: &EditBatch) {
+        if batch.is_empty() {
+            return;
+        }
+
+        // CRITICAL: Adjust markers BEFORE modifying buffer
+        self.marker_list.adjust_for_edits(batch);
+        self.margins.adjust_for_edits(batch);
+
+        self.buffer.apply_bulk_edits(&batch.bulk_edits());
+
+        for cursor_id in self.cursors.ids() {
+            let Some(cursor) = self.cursors.get_mut(cursor_id) else {
+                continue;
+            };
+            let (position, anchor) = batch.map_cursor(cursor_id, cursor);
+            cursor.position = position;
+            cursor.anchor = anchor;
+            if let Some(sticky_column) = batch.sticky_column(cursor_id) {
+                cursor.sticky_column = sticky_column;
+            }
+        }
+        self.cursors.merge_duplicates();
+
+        self.highlighter.invalidate_all();
+        self.update_primary_cursor_line_number();
+    }
+
+    /// Work out the primary cursor's line number again after a bulk change
+    fn update_primary_cursor_line_number(&mut self) {
+        let primary_pos = self.cursors.primary().position;
+        self.primary_cursor_line_number = match self.buffer.offset_to_position(primary_pos) {
+            Some(pos) => LineNumber::Absolute(pos.line),
+            None => LineNumber::Absolute(0),
+        };
+    }
+
     /// Apply an event to the state - THE ONLY WAY TO MODIFY STATE
     /// This is the heart of the event-driven architecture
     pub fn apply(&mut self, event: &Event) {
@@ -538,7 +637,7 @@ impl EditorState {
                     overlay.handle
                 );
 
-                self.overlays.add(overlay);
+                self.overlays.add(overlay, &self.marker_list);
             }
 
             Event::RemoveOverlay { handle } => {
@@ -668,24 +767,36 @@ impl EditorState {
                     self.buffer.restore_piece_tree(tree);
                 }
 
+                // Cursors the edit merged come back on undo and go again on
+                // redo. Both lists hold every cursor there was
+                let primary_id = self.cursors.primary_id();
+                let kept: std::collections::HashSet<crate::model::event::CursorId> = new_cursors
+                    .iter()
+                    .map(|(cursor_id, ..)| *cursor_id)
+                    .collect();
+                for cursor_id in self.cursors.ids() {
+                    if !kept.contains(&cursor_id) {
+                        self.cursors.remove(cursor_id);
+                    }
+                }
+
                 // Update cursor positions
                 for (cursor_id, position, anchor) in new_cursors {
                     if let Some(cursor) = self.cursors.get_mut(*cursor_id) {
                         cursor.position = *position;
                         cursor.anchor = *anchor;
+                    } else {
+                        let mut cursor = Cursor::new(*position);
+                        cursor.anchor = *anchor;
+                        self.cursors.insert_with_id(*cursor_id, cursor);
                     }
                 }
+                self.cursors.set_primary(primary_id);
 
                 // Invalidate highlight cache for entire buffer
                 self.highlighter.invalidate_all();
 
-                // Update primary cursor line number
-                let primary_pos = self.cursors.primary().position;
-                self.primary_cursor_line_number = match self.buffer.offset_to_position(primary_pos)
-                {
-                    Some(pos) => crate::model::buffer::LineNumber::Absolute(pos.line),
-                    None => crate::model::buffer::LineNumber::Absolute(0),
-                };
+                self.update_primary_cursor_line_number();
             }
         }
     }
@@ -715,6 +826,35 @@ impl EditorState {
             tracing::debug!("Dismissed transient popup on buffer focus loss");
         }
     }
+
+    /// Check the invariants tying cursors, overlays and virtual text to the
+    /// buffer, describing the first violation found
+    pub fn check_invariants(&self) -> std::result::Result<(), String> {
+        let len = self.buffer.len();
+        self.check_cursors(&self.cursors)?;
+        self.overlays.check_invariants(&self.marker_list, len)?;
+        self.virtual_texts.check_invariants(&self.marker_list, len)
+    }
+
+    /// Check that a cursor set (this buffer's or a split's view of it) fits
+    /// the buffer, with every cursor on a character boundary
+    pub fn check_cursors(&self, cursors: &Cursors) -> std::result::Result<(), String> {
+        cursors.check_invariants(self.buffer.len())?;
+        if self.buffer.is_binary() {
+            return Ok(());
+        }
+        for (id, cursor) in cursors.iter() {
+            for pos in std::iter::once(cursor.position).chain(cursor.anchor) {
+                if self.buffer.snap_to_char_boundary(pos) != pos {
+                    return Err(format!(
+                        "cursor {:?} has a position {} inside a character",
+                        id, pos
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
 }
 
 /// Convert event overlay face to the actual overlay face
@@ -746,10 +886,6 @@ fn convert_event_face_to_overlay_face(event_face: &EventOverlayFace) -> OverlayF
             underline,
         } => {
             use ratatui::style::Modifier;
-            let mut style = Style::default().fg(Color::Rgb(color.0, color.1, color.2));
-            if let Some(bg) = bg_color {
-                style = style.bg(Color::Rgb(bg.0, bg.1, bg.2));
-            }
             let mut modifiers = Modifier::empty();
             if *bold {
                 modifiers |= Modifier::BOLD;
@@ -760,10 +896,16 @@ fn convert_event_face_to_overlay_face(event_face: &EventOverlayFace) -> OverlayF
             if *underline {
                 modifiers |= Modifier::UNDERLINED;
             }
-            if !modifiers.is_empty() {
-                style = style.add_modifier(modifiers);
+            let style = ThemedStyle {
+                fg: Some(color.clone()),
+                bg: bg_color.clone(),
+                add_modifier: modifiers,
+            };
+            // Theme colors are looked up when drawing, so they follow theme changes
+            match style.fixed() {
+                Some(style) => OverlayFace::Style { style },
+                None => OverlayFace::Themed { style },
             }
-            OverlayFace::Style { style }
         }
     }
 }
@@ -872,7 +1014,9 @@ impl EditorState {
             .buffer
             .get_text_range_mut(start, end.saturating_sub(start))
         {
-            Ok(bytes) => String::from_utf8_lossy(&bytes).into_owned(),
+            // Valid UTF-8 is moved into the String without copying it again
+            Ok(bytes) => String::from_utf8(bytes)
+                .unwrap_or_else(|e| String::from_utf8_lossy(e.as_bytes()).into_owned()),
             Err(e) => {
                 tracing::warn!("Failed to get text range {}..{}: {}", start, end, e);
                 String::new()
@@ -1144,6 +1288,78 @@ mod tests {
     use super::*;
     use crate::model::event::CursorId;
 
+    fn state_with_text(text: &str) -> EditorState {
+        let mut state =
+            EditorState::new(80, 24, crate::config::LARGE_FILE_THRESHOLD_BYTES as usize);
+        let cursor_id = state.cursors.primary_id();
+        state.apply(&Event::Insert {
+            position: 0,
+            text: text.to_string(),
+            cursor_id,
+        });
+        state
+    }
+
+    #[test]
+    fn test_check_invariants_accepts_consistent_state() {
+        let mut state = state_with_text("héllo wörld");
+        state.cursors.add(Cursor::with_selection(0, 3));
+        let overlay = Overlay::new(
+            &mut state.marker_list,
+            0..5,
+            OverlayFace::Background { color: Color::Red },
+        );
+        state.overlays.add(overlay, &state.marker_list);
+        assert_eq!(state.check_invariants(), Ok(()));
+    }
+
+    #[test]
+    fn test_check_invariants_catches_cursor_past_end() {
+        let mut state = state_with_text("hello");
+        let id = state.cursors.primary_id();
+        state.cursors.primary_mut().position = 42;
+        let err = state.check_invariants().unwrap_err();
+        assert!(err.contains(&format!("cursor {:?} at 42", id)), "{}", err);
+    }
+
+    #[test]
+    fn test_check_invariants_catches_cursor_inside_character() {
+        let mut state = state_with_text("héllo");
+        state.cursors.primary_mut().position = 2; // second byte of 'é'
+        let err = state.check_invariants().unwrap_err();
+        assert!(err.contains("inside a character"), "{}", err);
+    }
+
+    #[test]
+    fn test_check_invariants_catches_overlay_with_deleted_marker() {
+        let mut state = state_with_text("hello");
+        let overlay = Overlay::new(
+            &mut state.marker_list,
+            1..3,
+            OverlayFace::Background { color: Color::Red },
+        );
+        let end_marker = overlay.end_marker;
+        let handle = state.overlays.add(overlay, &state.marker_list);
+        state.marker_list.delete(end_marker);
+        let err = state.check_invariants().unwrap_err();
+        assert!(err.contains(&format!("overlay {}", handle)), "{}", err);
+    }
+
+    #[test]
+    fn test_check_invariants_catches_virtual_text_past_end() {
+        let mut state = state_with_text("hello");
+        state.virtual_texts.add(
+            &mut state.marker_list,
+            50,
+            "hint".to_string(),
+            Style::default(),
+            crate::view::virtual_text::VirtualTextPosition::AfterChar,
+            0,
+        );
+        let err = state.check_invariants().unwrap_err();
+        assert!(err.contains("virtual text 0 at 50"), "{}", err);
+    }
+
     #[test]
     fn test_state_new() {
         let state = EditorState::new(80, 24, crate::config::LARGE_FILE_THRESHOLD_BYTES as usize);
@@ -1701,4 +1917,183 @@ mod tests {
             assert!(results.is_empty());
         }
     }
+
+    mod edit_batch_properties {
+        use super::*;
+        use crate::model::edit_batch::EditBatch;
+        use proptest::prelude::*;
+
+        /// What every cursor does in one keystroke
+        #[derive(Debug, Clone)]
+        enum CursorEdit {
+            Type(String),
+            Backspace,
+            /// Each cursor pastes a different text
+            Paste,
+            /// Insert a pair of brackets and step back between them
+            Brackets,
+            /// Each cursor selects up to the next one and types over it
+            ReplaceSelection(String),
+        }
+
+        fn cursor_edit() -> impl Strategy<Value = CursorEdit> {
+            prop_oneof![
+                "[a-z\n]{1,3}".prop_map(CursorEdit::Type),
+                Just(CursorEdit::Backspace),
+                Just(CursorEdit::Paste),
+                Just(CursorEdit::Brackets),
+                "[a-z]{0,3}".prop_map(CursorEdit::ReplaceSelection),
+            ]
+        }
+
+        /// A state holding `text` with a cursor for each of `cursors`, as
+        /// (position, anchor), and a marker at each of `markers`
+        fn state_with_cursors(
+            text: &str,
+            cursors: &[(usize, Option<usize>)],
+            markers: &[usize],
+        ) -> EditorState {
+            let mut state = state_with_text(text);
+            let (position, anchor) = cursors[0];
+            let primary = state.cursors.primary_mut();
+            primary.position = position;
+            primary.anchor = anchor;
+            for &(position, anchor) in &cursors[1..] {
+                let mut cursor = Cursor::new(position);
+                cursor.anchor = anchor;
+                state.cursors.add(cursor);
+            }
+            for &position in markers {
+                state.marker_list.create(position, false);
+            }
+            state
+        }
+
+        /// The events the cursors generate for one keystroke, last cursor
+        /// first, as `action_to_events` generates them
+        fn cursor_events(
+            edit: &CursorEdit,
+            text: &str,
+            cursors: &[(CursorId, usize, Option<usize>)],
+        ) -> Vec<Event> {
+            let mut events = Vec::new();
+            for (i, &(cursor_id, position, anchor)) in cursors.iter().enumerate().rev() {
+                match edit {
+                    CursorEdit::Type(typed) => events.push(Event::Insert {
+                        position,
+                        text: typed.clone(),
+                        cursor_id,
+                    }),
+                    CursorEdit::Backspace if position > 0 => events.push(Event::Delete {
+                        range: position - 1..position,
+                        deleted_text: text[position - 1..position].to_string(),
+                        cursor_id,
+                    }),
+                    CursorEdit::Backspace => {}
+                    CursorEdit::Paste => events.push(Event::Insert {
+                        position,
+                        text: format!("<{}>", i),
+                        cursor_id,
+                    }),
+                    CursorEdit::Brackets => {
+                        events.push(Event::Insert {
+                            position,
+                            text: "()".to_string(),
+                            cursor_id,
+                        });
+                        events.push(Event::MoveCursor {
+                            cursor_id,
+                            old_position: position + 2,
+                            new_position: position + 1,
+                            old_anchor: None,
+                            new_anchor: None,
+                            old_sticky_column: 0,
+                            new_sticky_column: 0,
+                        });
+                    }
+                    CursorEdit::ReplaceSelection(typed) => {
+                        let start = anchor.unwrap_or(position);
+                        events.push(Event::Delete {
+                            range: start..position,
+                            deleted_text: text[start..position].to_string(),
+                            cursor_id,
+                        });
+                        events.push(Event::Insert {
+                            position: start,
+                            text: typed.clone(),
+                            cursor_id,
+                        });
+                    }
+                }
+            }
+            events
+        }
+
+        fn cursor_states(state: &EditorState) -> Vec<(CursorId, usize, Option<usize>)> {
+            let mut cursors: Vec<_> = state
+                .cursors
+                .iter()
+                .map(|(id, cursor)| (id, cursor.position, cursor.anchor))
+                .collect();
+            cursors.sort_by_key(|(id, position, anchor)| (*position, *anchor, id.0));
+            cursors
+        }
+
+        proptest! {
+            /// Applying a keystroke's edits as one batch leaves the text,
+            /// cursors and markers as applying them one at a time does
+            #[test]
+            fn prop_batch_matches_sequential_edits(
+                text in "[a-z\n]{0,200}",
+                positions in prop::collection::vec(0..=200usize, 1..40),
+                marker_positions in prop::collection::vec(0..=200usize, 0..20),
+                edit in cursor_edit(),
+            ) {
+                let len = text.len();
+                let mut positions: Vec<usize> = positions.iter().map(|p| p % (len + 1)).collect();
+                positions.sort_unstable();
+                positions.dedup();
+                let cursors: Vec<(usize, Option<usize>)> =
+                    if matches!(edit, CursorEdit::ReplaceSelection(_)) {
+                        // Selections up to the next cursor, so none overlap
+                        positions
+                            .windows(2)
+                            .step_by(2)
+                            .map(|pair| (pair[1], Some(pair[0])))
+                            .collect()
+                    } else {
+                        positions.iter().map(|&p| (p, None)).collect()
+                    };
+                prop_assume!(!cursors.is_empty());
+                let mut markers: Vec<usize> =
+                    marker_positions.iter().map(|p| p % (len + 1)).collect();
+                markers.sort_unstable();
+                markers.dedup();
+
+                let mut sequential = state_with_cursors(&text, &cursors, &markers);
+                let mut batched = state_with_cursors(&text, &cursors, &markers);
+                let events = cursor_events(&edit, &text, &cursor_states(&sequential));
+
+                for event in &events {
+                    sequential.apply(event);
+                }
+                sequential.cursors.merge_duplicates();
+                batched.apply_edit_batch(&EditBatch::from_cursor_events(&events));
+
+                prop_assert_eq!(batched.buffer.to_string(), sequential.buffer.to_string());
+                prop_assert_eq!(cursor_states(&batched), cursor_states(&sequential));
+                prop_assert_eq!(batched.cursors.primary_id(), sequential.cursors.primary_id());
+                let marker_positions = |state: &EditorState| {
+                    state
+                        .marker_list
+                        .query_range(0, usize::MAX)
+                        .into_iter()
+                        .map(|(id, start, end)| (id.0, start, end))
+                        .collect::<std::collections::BTreeSet<_>>()
+                };
+                prop_assert_eq!(marker_positions(&batched), marker_positions(&sequential));
+                prop_assert_eq!(batched.check_invariants(), Ok(()));
+            }
+        }
+    }
 }
@@ -101,4 +101,112 @@ pub struct LspServerConfig {
     /// These are passed in the `initializationOptions` field of the LSP Initialize request
     #[serde(default)]
     pub initialization_options: Option<serde_json::Value>,
+
+    /// Name of the server among its language's servers (default: the
+    /// command's file name)
+    #[serde(default)]
+    pub name: Option<String>,
+
+    /// Whether this server answers the requests only one server can answer
+    /// (rename, formatting as you type, signature help, inlay hints) when
+    /// the language has several; the first server does otherwise
+    #[serde(default)]
+    pub primary: bool,
+}
+
+impl LspServerConfig {
+    /// Name of the server among its language's servers
+    pub fn server_name(&self) -> String {
+        if let Some(name) = &self.name {
+            return name.clone();
+        }
+        std::path::Path::new(&self.command)
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_else(|| self.command.clone())
+    }
+}
+
+/// LSP servers of one language: a single server, or a list of servers that
+/// are all attached to the language's buffers
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(untagged)]
+pub enum LspLanguageConfig {
+    Single(LspServerConfig),
+    Multi(Vec<LspServerConfig>),
+}
+
+impl LspLanguageConfig {
+    /// The servers, in the order they were configured
+    pub fn servers(&self) -> &[LspServerConfig] {
+        match self {
+            Self::Single(server) => std::slice::from_ref(server),
+            Self::Multi(servers) => servers,
+        }
+    }
+
+    pub fn servers_mut(&mut self) -> &mut [LspServerConfig] {
+        match self {
+            Self::Single(server) => std::slice::from_mut(server),
+            Self::Multi(servers) => servers,
+        }
+    }
+
+    /// The server named `name`
+    pub fn server(&self, name: &str) -> Option<&LspServerConfig> {
+        self.servers()
+            .iter()
+            .find(|server| server.server_name() == name)
+    }
+
+    pub fn server_mut(&mut self, name: &str) -> Option<&mut LspServerConfig> {
+        self.servers_mut()
+            .iter_mut()
+            .find(|server| server.server_name() == name)
+    }
+
+    /// The server answering requests only one server can answer: the one
+    /// marked primary, else the first
+    pub fn primary(&self) -> Option<&LspServerConfig> {
+        let servers = self.servers();
+        servers
+            .iter()
+            .find(|server| server.primary)
+            .or_else(|| servers.first())
+    }
+
+    /// The servers with the primary one first, then the others in the order
+    /// they were configured
+    pub fn ranked(&self) -> Vec<&LspServerConfig> {
+        let primary = self.primary();
+        primary
+            .into_iter()
+            .chain(
+                self.servers()
+                    .iter()
+                    .filter(|server| !primary.is_some_and(|p| std::ptr::eq(*server, p))),
+            )
+            .collect()
+    }
+
+    /// Whether any of the servers is enabled
+    pub fn any_enabled(&self) -> bool {
+        self.servers().iter().any(|server| server.enabled)
+    }
+
+    /// Label of `server` in statuses, messages and commands: the language
+    /// when it is the language's only server, else `language/name`
+    pub fn label(&self, language: &str, server: &LspServerConfig) -> String {
+        if self.servers().len() > 1 {
+            format!("{}/{}", language, server.server_name())
+        } else {
+            language.to_string()
+        }
+    }
+}
+
+impl From<LspServerConfig> for LspLanguageConfig {
+    fn from(server: LspServerConfig) -> Self {
+        Self::Single(server)
+    }
 }
@@ -11,17 +11,40 @@
 //!
 //! Without this, creating multiple Editor instances sequentially causes segfaults
 //! because V8 cannot be reinitialized after disposal.
+//!
+//! Initialization can also fail outright on locked-down systems (no /proc, strict
+//! seccomp profiles, no executable memory). A panic is caught and reported as an
+//! error so the editor can run without plugins; a failed attempt is not
+//! remembered, so a later call tries again.
 
-use std::sync::Once;
+use std::sync::Mutex;
 
-static INIT_V8: Once = Once::new();
+static V8_INITIALIZED: Mutex<bool> = Mutex::new(false);
 
 /// Initialize V8 platform exactly once per process.
 ///
 /// This must be called before creating any JsRuntime instances.
-/// Safe to call multiple times - only the first call has any effect.
-pub fn init() {
-    INIT_V8.call_once(|| {
-        deno_core::JsRuntime::init_platform(None, false);
-    });
+/// Safe to call multiple times - only the first successful call has any effect.
+pub fn init() -> Result<(), String> {
+    let mut initialized = V8_INITIALIZED
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    if *initialized {
+        return Ok(());
+    }
+    std::panic::catch_unwind(|| deno_core::JsRuntime::init_platform(None, false))
+        .map_err(|panic| panic_message(panic.as_ref()))?;
+    *initialized = true;
+    Ok(())
+}
+
+/// Text of a caught panic payload
+pub fn panic_message(panic: &(dyn std::any::Any + Send)) -> String {
+    if let Some(message) = panic.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = panic.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "unknown panic".to_string()
+    }
 }
@@ -0,0 +1,103 @@
+//! Drawing East Asian ambiguous-width characters two cells wide
+//!
+//! Ratatui places text by the widths western terminals use, where ambiguous
+//! characters (box drawing, circled digits, ...) take one cell. With
+//! `editor.ambiguous_width` set to "wide" the editor's layout counts them as
+//! two cells, the way CJK terminals draw them. Text the editor laid out
+//! itself gets a padding cell after each such character, and a last pass over
+//! the frame keeps the cell after every ambiguous character from being drawn
+//! over the character's second half.
+
+use crate::primitives::display_width::{ambiguous_wide, is_ambiguous_width};
+use ratatui::buffer::Buffer;
+use ratatui::text::Line;
+
+/// Add a padding space after every ambiguous-width character of `lines`,
+/// so each takes the two cells the editor's layout gave it
+///
+/// Does nothing unless ambiguous characters are drawn wide.
+pub fn pad_lines(lines: &mut [Line<'_>]) {
+    if !ambiguous_wide() {
+        return;
+    }
+    for span in lines.iter_mut().flat_map(|line| line.spans.iter_mut()) {
+        if !span.content.chars().any(is_ambiguous_width) {
+            continue;
+        }
+        let mut padded = String::with_capacity(span.content.len() * 2);
+        for c in span.content.chars() {
+            padded.push(c);
+            if is_ambiguous_width(c) {
+                padded.push(' ');
+            }
+        }
+        span.content = padded.into();
+    }
+}
+
+/// Leave the cell after every ambiguous-width character undrawn
+///
+/// The terminal draws the character over that cell too, and writing the
+/// cell would shift the rest of the row. Does nothing unless ambiguous
+/// characters are drawn wide.
+pub fn skip_covered_cells(buf: &mut Buffer) {
+    if !ambiguous_wide() {
+        return;
+    }
+    let area = buf.area;
+    for y in area.top()..area.bottom() {
+        let mut x = area.left();
+        while x < area.right() {
+            let covers_next = buf[(x, y)]
+                .symbol()
+                .chars()
+                .next()
+                .is_some_and(is_ambiguous_width);
+            if covers_next && x + 1 < area.right() {
+                buf[(x + 1, y)].set_skip(true);
+                x += 2;
+            } else {
+                x += 1;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::primitives::display_width::set_ambiguous_wide;
+    use ratatui::layout::Rect;
+    use ratatui::style::Style;
+    use ratatui::text::Span;
+
+    #[test]
+    fn test_narrow_mode_leaves_text_alone() {
+        set_ambiguous_wide(false);
+        let mut lines = vec![Line::from("①x")];
+        pad_lines(&mut lines);
+        assert_eq!(lines[0].spans[0].content, "①x");
+
+        let mut buf = Buffer::empty(Rect::new(0, 0, 4, 1));
+        buf.set_string(0, 0, "①x", Style::default());
+        skip_covered_cells(&mut buf);
+        assert!(!buf[(1, 0)].skip);
+    }
+
+    #[test]
+    fn test_wide_mode_pads_and_skips_covered_cells() {
+        set_ambiguous_wide(true);
+        let mut lines = vec![Line::from(vec![Span::raw("a①"), Span::raw("─b")])];
+        pad_lines(&mut lines);
+        assert_eq!(lines[0].spans[0].content, "a① ");
+        assert_eq!(lines[0].spans[1].content, "─ b");
+
+        let mut buf = Buffer::empty(Rect::new(0, 0, 8, 1));
+        buf.set_line(0, 0, &lines[0], 8);
+        skip_covered_cells(&mut buf);
+        let skipped: Vec<u16> = (0..8).filter(|&x| buf[(x, 0)].skip).collect();
+        assert_eq!(skipped, vec![2, 4]);
+        assert_eq!(buf[(5, 0)].symbol(), "b");
+        set_ambiguous_wide(false);
+    }
+}
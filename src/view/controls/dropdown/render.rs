@@ -8,6 +8,8 @@ use ratatui::Frame;
 
 use super::{DropdownColors, DropdownLayout, DropdownState, FocusState};
 
+use crate::view::glyphs::glyphs;
+
 /// Render a dropdown control (closed state)
 ///
 /// # Arguments
@@ -76,7 +78,11 @@ pub fn render_dropdown_aligned(
     let display_width = max_option_len.max(selected_text.len()).min(20);
     let padded = format!("{:width$}", selected_text, width = display_width);
 
-    let arrow = if state.open { "▲" } else { "▼" };
+    let arrow = if state.open {
+        glyphs().arrow_up
+    } else {
+        glyphs().arrow_down
+    };
 
     let actual_label_width = label_width.unwrap_or(state.label.len() as u16);
     let padded_label = format!(
@@ -168,9 +174,9 @@ pub fn render_dropdown_aligned(
 
             for i in 0..available_height {
                 let scrollbar_char = if i >= thumb_pos && i < thumb_pos + thumb_size {
-                    "█"
+                    glyphs().scrollbar_thumb
                 } else {
-                    "░"
+                    glyphs().scrollbar_track
                 };
                 let scrollbar_area = Rect::new(scrollbar_x, menu_y + i as u16, 1, 1);
                 let scrollbar_span =
@@ -7,6 +7,7 @@ use ratatui::widgets::Paragraph;
 use ratatui::Frame;
 
 use super::{FocusState, KeybindingListColors, KeybindingListLayout, KeybindingListState};
+use crate::view::glyphs::glyphs;
 use serde_json::Value;
 
 /// Render a keybinding list control
@@ -67,9 +68,15 @@ pub fn render_keybinding_list(
                 format!("{:<20}", key_combo),
                 Style::default().fg(colors.key_fg).bg(bg),
             ),
-            Span::styled(" → ", Style::default().fg(colors.label_fg).bg(bg)),
+            Span::styled(
+                format!(" {} ", glyphs().maps_to),
+                Style::default().fg(colors.label_fg).bg(bg),
+            ),
             Span::styled(action, Style::default().fg(colors.action_fg).bg(bg)),
-            Span::styled(" [x]", Style::default().fg(colors.delete_fg).bg(bg)),
+            Span::styled(
+                format!(" {}", glyphs().remove_button),
+                Style::default().fg(colors.delete_fg).bg(bg),
+            ),
         ]);
         frame.render_widget(Paragraph::new(line), entry_area);
 
@@ -94,7 +101,10 @@ pub fn render_keybinding_list(
         let indicator = if is_add_focused { "> " } else { "  " };
         let line = Line::from(vec![
             Span::styled(indicator, Style::default().fg(colors.label_fg).bg(bg)),
-            Span::styled("[+] Add new", Style::default().fg(colors.add_fg).bg(bg)),
+            Span::styled(
+                format!("{} Add new", glyphs().add_button),
+                Style::default().fg(colors.add_fg).bg(bg),
+            ),
         ]);
         frame.render_widget(Paragraph::new(line), add_area);
     }
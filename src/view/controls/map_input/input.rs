@@ -71,8 +71,8 @@ impl MapState {
 
         match key.code {
             KeyCode::Enter => {
-                if self.focused_entry.is_none() && !self.new_key_text.is_empty() {
-                    let key = self.new_key_text.clone();
+                if self.focused_entry.is_none() && !self.new_key.is_empty() {
+                    let key = self.new_key.value.clone();
                     self.add_entry_from_input();
                     Some(MapEvent::EntryAdded(key))
                 } else if let Some(index) = self.focused_entry {
@@ -83,29 +83,10 @@ impl MapState {
                     None
                 }
             }
-            KeyCode::Delete => {
-                if let Some(index) = self.focused_entry {
-                    self.remove_entry(index);
-                    Some(MapEvent::EntryRemoved(index))
-                } else {
-                    None
-                }
-            }
-            KeyCode::Backspace => {
-                if self.focused_entry.is_none() && self.cursor > 0 {
-                    self.backspace();
-                    Some(MapEvent::NewKeyChanged(self.new_key_text.clone()))
-                } else {
-                    None
-                }
-            }
-            KeyCode::Left => {
-                self.move_left();
-                None
-            }
-            KeyCode::Right => {
-                self.move_right();
-                None
+            KeyCode::Delete if self.focused_entry.is_some() => {
+                let index = self.focused_entry?;
+                self.remove_entry(index);
+                Some(MapEvent::EntryRemoved(index))
             }
             KeyCode::Up => {
                 self.focus_prev();
@@ -124,13 +105,10 @@ impl MapState {
                     None
                 }
             }
-            KeyCode::Char(c) => {
-                if self.focused_entry.is_none() {
-                    self.insert(c);
-                    Some(MapEvent::NewKeyChanged(self.new_key_text.clone()))
-                } else {
-                    None
-                }
+            _ if self.focused_entry.is_none() => {
+                // Editing keys apply to the new key field
+                let changed = self.new_key.handle_key(&key)?;
+                changed.then(|| MapEvent::NewKeyChanged(self.new_key.value.clone()))
             }
             _ => None,
         }
@@ -193,7 +171,7 @@ mod tests {
     #[test]
     fn test_click_add_row() {
         let mut state = MapState::new("Test");
-        state.new_key_text = "newkey".to_string();
+        state.new_key.set_value("newkey");
         let layout = make_layout();
 
         // Clicking on the add row focuses it
@@ -220,7 +198,7 @@ mod tests {
     #[test]
     fn test_enter_adds_entry() {
         let mut state = MapState::new("Test");
-        state.new_key_text = "newkey".to_string();
+        state.new_key.set_value("newkey");
 
         let enter = KeyEvent::new(KeyCode::Enter, KeyModifiers::empty());
         let result = state.handle_key(enter);
@@ -248,7 +226,29 @@ mod tests {
         let key = KeyEvent::new(KeyCode::Char('a'), KeyModifiers::empty());
         let result = state.handle_key(key);
         assert_eq!(result, Some(MapEvent::NewKeyChanged("a".to_string())));
-        assert_eq!(state.new_key_text, "a");
+        assert_eq!(state.new_key_text(), "a");
+    }
+
+    #[test]
+    fn test_paste_key_name() {
+        let mut state = MapState::new("Test");
+        state.focus_new_entry();
+        state.insert('x');
+
+        // Pasting over a selection replaces it; the trailing newline is stripped
+        let select_all = KeyEvent::new(KeyCode::Char('a'), KeyModifiers::CONTROL);
+        assert_eq!(state.handle_key(select_all), None);
+        state.insert_str("rust-analyzer\n");
+        assert_eq!(state.new_key_text(), "rust-analyzer");
+
+        let enter = KeyEvent::new(KeyCode::Enter, KeyModifiers::empty());
+        let result = state.handle_key(enter);
+        assert_eq!(
+            result,
+            Some(MapEvent::EntryAdded("rust-analyzer".to_string()))
+        );
+        assert_eq!(state.entries[0].0, "rust-analyzer");
+        assert!(state.new_key_text().is_empty());
     }
 
     #[test]
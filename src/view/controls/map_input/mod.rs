@@ -27,6 +27,17 @@ pub use input::MapEvent;
 pub use render::render_map;
 
 use super::FocusState;
+use crate::primitives::display_width::{byte_offset_at_visual_column, str_width};
+use crate::view::ui::line_edit::LineEdit;
+
+/// Shorten a preview to `max_width` columns, ending it with "..."
+fn truncate_preview(preview: String, max_width: usize) -> String {
+    if str_width(&preview) <= max_width {
+        return preview;
+    }
+    let end = byte_offset_at_visual_column(&preview, max_width.saturating_sub(3));
+    format!("{}...", &preview[..end])
+}
 
 /// State for a map/dictionary control
 #[derive(Debug, Clone)]
@@ -35,10 +46,8 @@ pub struct MapState {
     pub entries: Vec<(String, serde_json::Value)>,
     /// Currently focused entry index (None = add-new field)
     pub focused_entry: Option<usize>,
-    /// Text in the "add new" key field
-    pub new_key_text: String,
-    /// Cursor position in the new key field
-    pub cursor: usize,
+    /// The "add new" key field
+    pub new_key: LineEdit,
     /// Label for this map
     pub label: String,
     /// Focus state
@@ -57,8 +66,7 @@ impl MapState {
         Self {
             entries: Vec::new(),
             focused_entry: None,
-            new_key_text: String::new(),
-            cursor: 0,
+            new_key: LineEdit::new(),
             label: label.into(),
             focus: FocusState::Normal,
             expanded: Vec::new(),
@@ -95,6 +103,22 @@ impl MapState {
         }
     }
 
+    /// Display value for an entry, shortened to `max_width` columns
+    pub fn display_preview(&self, value: &serde_json::Value, max_width: usize) -> String {
+        truncate_preview(self.get_display_value(value), max_width)
+    }
+
+    /// Whether an entry's value has the type the value schema expects.
+    ///
+    /// Entries that don't (e.g. left by another version of the editor) are shown
+    /// as raw JSON but not opened in the entry dialog, which would replace them
+    /// with defaults.
+    pub fn entry_matches_schema(&self, value: &serde_json::Value) -> bool {
+        self.value_schema.as_ref().map_or(true, |schema| {
+            crate::view::settings::reconcile::value_matches(&schema.setting_type, value)
+        })
+    }
+
     /// Set the entries from JSON value
     pub fn with_entries(mut self, value: &serde_json::Value) -> Self {
         if let Some(obj) = value.as_object() {
@@ -142,13 +166,12 @@ impl MapState {
         self.entries.sort_by(|a, b| a.0.cmp(&b.0));
     }
 
-    /// Add entry from the new_key_text field with default value
+    /// Add entry from the new key field with default value
     pub fn add_entry_from_input(&mut self) {
-        if self.new_key_text.is_empty() {
+        if self.new_key.is_empty() {
             return;
         }
-        let key = std::mem::take(&mut self.new_key_text);
-        self.cursor = 0;
+        let key = self.new_key.take();
         // Use an empty object as default value
         self.add_entry(key, serde_json::json!({}));
     }
@@ -189,7 +212,7 @@ impl MapState {
     /// Focus on the new entry field
     pub fn focus_new_entry(&mut self) {
         self.focused_entry = None;
-        self.cursor = self.new_key_text.len();
+        self.new_key.move_end();
     }
 
     /// Toggle expansion of an entry
@@ -209,36 +232,45 @@ impl MapState {
         self.expanded.contains(&index)
     }
 
+    /// Text in the new key field
+    pub fn new_key_text(&self) -> &str {
+        &self.new_key.value
+    }
+
+    /// Whether the new key field accepts edits
+    fn new_key_editable(&self) -> bool {
+        self.focus != FocusState::Disabled && self.focused_entry.is_none()
+    }
+
     /// Insert a character in the new key field
     pub fn insert(&mut self, c: char) {
-        if self.focus == FocusState::Disabled || self.focused_entry.is_some() {
-            return;
+        if self.new_key_editable() {
+            self.new_key.insert(c);
+        }
+    }
+
+    /// Insert a string in the new key field (control characters are stripped)
+    pub fn insert_str(&mut self, s: &str) {
+        if self.new_key_editable() {
+            self.new_key.insert_str(s);
         }
-        self.new_key_text.insert(self.cursor, c);
-        self.cursor += 1;
     }
 
     /// Backspace in the new key field
     pub fn backspace(&mut self) {
-        if self.focus == FocusState::Disabled || self.focused_entry.is_some() || self.cursor == 0 {
-            return;
+        if self.new_key_editable() {
+            self.new_key.backspace();
         }
-        self.cursor -= 1;
-        self.new_key_text.remove(self.cursor);
     }
 
     /// Move cursor left
     pub fn move_left(&mut self) {
-        if self.cursor > 0 {
-            self.cursor -= 1;
-        }
+        self.new_key.move_left();
     }
 
     /// Move cursor right
     pub fn move_right(&mut self) {
-        if self.cursor < self.new_key_text.len() {
-            self.cursor += 1;
-        }
+        self.new_key.move_right();
     }
 
     /// Move focus to previous entry. Returns true if handled, false if should move to prev item.
@@ -265,8 +297,7 @@ impl MapState {
                 true
             }
             Some(_) => {
-                self.focused_entry = None;
-                self.cursor = self.new_key_text.len();
+                self.focus_new_entry();
                 true
             }
             None => false, // At add-new, move to next item
@@ -279,8 +310,7 @@ impl MapState {
         if from_above && !self.entries.is_empty() {
             self.focused_entry = Some(0);
         } else {
-            self.focused_entry = None;
-            self.cursor = self.new_key_text.len();
+            self.focus_new_entry();
         }
     }
 
@@ -316,6 +346,7 @@ pub struct MapColors {
     pub add_button: Color,
     pub focused: Color,
     pub cursor: Color,
+    pub selection: Color,
     pub disabled: Color,
     pub expand_arrow: Color,
 }
@@ -331,6 +362,7 @@ impl Default for MapColors {
             add_button: Color::Green,
             focused: Color::Yellow,
             cursor: Color::Yellow,
+            selection: Color::DarkGray,
             disabled: Color::DarkGray,
             expand_arrow: Color::White,
         }
@@ -348,6 +380,7 @@ impl MapColors {
             add_button: theme.diagnostic_info_fg,
             focused: theme.selection_bg,
             cursor: theme.cursor,
+            selection: theme.selection_bg,
             disabled: theme.line_number_fg,
             expand_arrow: theme.editor_fg,
         }
@@ -482,6 +515,34 @@ mod tests {
         assert!(!state.is_expanded(0));
     }
 
+    #[test]
+    fn test_map_entry_not_matching_value_schema() {
+        use crate::view::settings::schema::{SettingSchema, SettingType};
+
+        let state = MapState::new("Languages")
+            .with_entries(&serde_json::json!({
+                "rust": { "tab_size": 4 },
+                "old": "überall überall überall überall"
+            }))
+            .with_value_schema(SettingSchema {
+                path: String::new(),
+                name: "Language".to_string(),
+                description: None,
+                setting_type: SettingType::Object {
+                    properties: Vec::new(),
+                },
+                default: None,
+            });
+
+        assert!(!state.entry_matches_schema(&state.entries[0].1));
+        assert!(state.entry_matches_schema(&state.entries[1].1));
+        // Multi-byte values are shortened on character boundaries
+        assert_eq!(
+            state.display_preview(&state.entries[0].1, 10),
+            "\"überal..."
+        );
+    }
+
     #[test]
     fn test_map_hit_test() {
         let layout = MapLayout {
@@ -1,5 +1,7 @@
 //! Map control rendering functions
 
+use crate::primitives::display_width::str_width;
+use crate::view::glyphs::glyphs;
 use ratatui::layout::Rect;
 use ratatui::style::{Modifier, Style};
 use ratatui::text::{Line, Span};
@@ -57,18 +59,15 @@ pub fn render_map(
         let is_focused = state.focused_entry == Some(idx) && state.focus == FocusState::Focused;
         let is_expanded = state.is_expanded(idx);
 
-        let arrow = if is_expanded { "▼" } else { ">" };
-
-        // Value preview using display_field if available
-        let value_preview = state.get_display_value(value);
-        // Truncate if too long
-        let max_preview_len = 30;
-        let value_preview = if value_preview.len() > max_preview_len {
-            format!("{}...", &value_preview[..max_preview_len - 3])
+        let arrow = if is_expanded {
+            glyphs().expanded
         } else {
-            value_preview
+            glyphs().collapsed
         };
 
+        // Value preview using display_field if available
+        let value_preview = state.display_preview(value, 30);
+
         let row_area = Rect::new(area.x, y, area.width, 1);
 
         // Full row background highlight for focused entry
@@ -182,27 +181,43 @@ pub fn render_map(
             (colors.border, colors.label)
         };
 
+        // Scrolls horizontally to keep the cursor visible
         let inner_width = actual_key_width.saturating_sub(2) as usize;
-        let visible: String = state.new_key_text.chars().take(inner_width).collect();
-        let padded = format!("{:width$}", visible, width = inner_width);
+        let visible = state.new_key.visible(inner_width);
+        let text_style = Style::default().fg(text_color);
 
-        let line = Line::from(vec![
+        let mut spans = vec![
             Span::raw(" ".repeat(indent as usize)),
             Span::styled("[", Style::default().fg(border_color)),
-            Span::styled(padded, Style::default().fg(text_color)),
+        ];
+        let mut visible_width = 0;
+        for (text, selected) in visible.runs {
+            visible_width += str_width(&text);
+            let style = if selected && is_focused {
+                text_style.bg(colors.selection)
+            } else {
+                text_style
+            };
+            spans.push(Span::styled(text, style));
+        }
+        spans.extend([
+            Span::raw(" ".repeat(inner_width.saturating_sub(visible_width))),
             Span::styled("]", Style::default().fg(border_color)),
             Span::raw(" "),
-            Span::styled("[+]", Style::default().fg(colors.add_button)),
+            Span::styled(glyphs().add_button, Style::default().fg(colors.add_button)),
             Span::raw(" Add entry..."),
         ]);
 
         let row_area = Rect::new(area.x, y, area.width, 1);
-        frame.render_widget(Paragraph::new(line), row_area);
+        frame.render_widget(Paragraph::new(Line::from(spans)), row_area);
 
         // Render cursor if focused
-        if is_focused && state.cursor <= inner_width {
-            let cursor_x = area.x + indent + 1 + state.cursor as u16;
-            let cursor_char = state.new_key_text.chars().nth(state.cursor).unwrap_or(' ');
+        if is_focused && visible.cursor_col < inner_width {
+            let cursor_x = area.x + indent + 1 + visible.cursor_col as u16;
+            let cursor_char = state.new_key.value[state.new_key.cursor..]
+                .chars()
+                .next()
+                .unwrap_or(' ');
             let cursor_area = Rect::new(cursor_x, y, 1, 1);
             let cursor_span = Span::styled(
                 cursor_char.to_string(),
@@ -235,9 +250,5 @@ pub(super) fn format_value_preview(value: &serde_json::Value, max_len: usize) ->
         serde_json::Value::Array(arr) => format!("[{} items]", arr.len()),
         serde_json::Value::Object(obj) => format!("{{{} fields}}", obj.len()),
     };
-    if s.len() > max_len {
-        format!("{}...", &s[..max_len - 3])
-    } else {
-        s
-    }
+    super::truncate_preview(s, max_len)
 }
@@ -1,6 +1,6 @@
 //! Text input handling
 
-use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+use crossterm::event::{KeyCode, KeyEvent, MouseButton, MouseEvent, MouseEventKind};
 
 use super::{FocusState, TextInputLayout, TextInputState};
 
@@ -83,53 +83,12 @@ impl TextInputState {
         }
 
         match key.code {
-            KeyCode::Enter => Some(TextInputEvent::Submitted(self.value.clone())),
+            KeyCode::Enter => Some(TextInputEvent::Submitted(self.value().to_string())),
             KeyCode::Esc => Some(TextInputEvent::Cancelled),
-            KeyCode::Backspace => {
-                if !self.value.is_empty() && self.cursor > 0 {
-                    self.backspace();
-                    Some(TextInputEvent::Changed(self.value.clone()))
-                } else {
-                    None
-                }
+            _ => {
+                let changed = self.edit.handle_key(&key)?;
+                changed.then(|| TextInputEvent::Changed(self.value().to_string()))
             }
-            KeyCode::Delete => {
-                if self.cursor < self.value.len() {
-                    self.delete();
-                    Some(TextInputEvent::Changed(self.value.clone()))
-                } else {
-                    None
-                }
-            }
-            KeyCode::Left => {
-                if key.modifiers.contains(KeyModifiers::CONTROL) {
-                    self.move_home();
-                } else {
-                    self.move_left();
-                }
-                None
-            }
-            KeyCode::Right => {
-                if key.modifiers.contains(KeyModifiers::CONTROL) {
-                    self.move_end();
-                } else {
-                    self.move_right();
-                }
-                None
-            }
-            KeyCode::Home => {
-                self.move_home();
-                None
-            }
-            KeyCode::End => {
-                self.move_end();
-                None
-            }
-            KeyCode::Char(c) => {
-                self.insert(c);
-                Some(TextInputEvent::Changed(self.value.clone()))
-            }
-            _ => None,
         }
     }
 }
@@ -137,6 +96,7 @@ impl TextInputState {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crossterm::event::KeyModifiers;
     use ratatui::layout::Rect;
 
     fn make_layout() -> TextInputLayout {
@@ -197,7 +157,7 @@ mod tests {
 
         let b = KeyEvent::new(KeyCode::Char('b'), KeyModifiers::empty());
         state.handle_key(b);
-        assert_eq!(state.value, "ab");
+        assert_eq!(state.value(), "ab");
     }
 
     #[test]
@@ -241,15 +201,15 @@ mod tests {
 
         let left = KeyEvent::new(KeyCode::Left, KeyModifiers::empty());
         state.handle_key(left);
-        assert_eq!(state.cursor, 4);
+        assert_eq!(state.cursor(), 4);
 
         let home = KeyEvent::new(KeyCode::Home, KeyModifiers::empty());
         state.handle_key(home);
-        assert_eq!(state.cursor, 0);
+        assert_eq!(state.cursor(), 0);
 
         let end = KeyEvent::new(KeyCode::End, KeyModifiers::empty());
         state.handle_key(end);
-        assert_eq!(state.cursor, 5);
+        assert_eq!(state.cursor(), 5);
     }
 
     #[test]
@@ -259,6 +219,6 @@ mod tests {
         let a = KeyEvent::new(KeyCode::Char('a'), KeyModifiers::empty());
         let result = state.handle_key(a);
         assert!(result.is_none());
-        assert!(state.value.is_empty());
+        assert!(state.value().is_empty());
     }
 }
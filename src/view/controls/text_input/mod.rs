@@ -11,7 +11,6 @@
 mod input;
 mod render;
 
-use crate::primitives::grapheme;
 use ratatui::layout::Rect;
 use ratatui::style::Color;
 
@@ -19,14 +18,13 @@ pub use input::TextInputEvent;
 pub use render::{render_text_input, render_text_input_aligned};
 
 use super::FocusState;
+use crate::view::ui::line_edit::LineEdit;
 
 /// State for a text input control
 #[derive(Debug, Clone)]
 pub struct TextInputState {
-    /// Current text value
-    pub value: String,
-    /// Cursor position (character index)
-    pub cursor: usize,
+    /// Text being edited, with cursor and selection
+    pub edit: LineEdit,
     /// Label displayed before the input
     pub label: String,
     /// Placeholder text when empty
@@ -41,8 +39,7 @@ impl TextInputState {
     /// Create a new text input state
     pub fn new(label: impl Into<String>) -> Self {
         Self {
-            value: String::new(),
-            cursor: 0,
+            edit: LineEdit::new(),
             label: label.into(),
             placeholder: String::new(),
             focus: FocusState::Normal,
@@ -59,7 +56,7 @@ impl TextInputState {
     /// Check if the current value is valid (valid JSON if validate_json is set)
     pub fn is_valid(&self) -> bool {
         if self.validate_json {
-            serde_json::from_str::<serde_json::Value>(&self.value).is_ok()
+            serde_json::from_str::<serde_json::Value>(self.value()).is_ok()
         } else {
             true
         }
@@ -67,8 +64,7 @@ impl TextInputState {
 
     /// Set the initial value
     pub fn with_value(mut self, value: impl Into<String>) -> Self {
-        self.value = value.into();
-        self.cursor = self.value.len();
+        self.edit.set_value(value);
         self
     }
 
@@ -89,48 +85,44 @@ impl TextInputState {
         self.focus != FocusState::Disabled
     }
 
+    /// Current text value
+    pub fn value(&self) -> &str {
+        &self.edit.value
+    }
+
+    /// Cursor position (byte offset)
+    pub fn cursor(&self) -> usize {
+        self.edit.cursor
+    }
+
     /// Insert a character at the cursor position
     pub fn insert(&mut self, c: char) {
-        if !self.is_enabled() {
-            return;
+        if self.is_enabled() {
+            self.edit.insert(c);
         }
-        self.value.insert(self.cursor, c);
-        self.cursor += c.len_utf8();
     }
 
-    /// Insert a string at the cursor position
+    /// Insert a string at the cursor position (control characters are stripped)
     pub fn insert_str(&mut self, s: &str) {
-        if !self.is_enabled() {
-            return;
+        if self.is_enabled() {
+            self.edit.insert_str(s);
         }
-        self.value.insert_str(self.cursor, s);
-        self.cursor += s.len();
     }
 
     /// Delete the character before the cursor (backspace)
     pub fn backspace(&mut self) {
-        if !self.is_enabled() || self.cursor == 0 {
-            return;
+        if self.is_enabled() {
+            self.edit.backspace();
         }
-        // Find the previous character boundary
-        let prev_boundary = self.value[..self.cursor]
-            .char_indices()
-            .next_back()
-            .map(|(i, _)| i)
-            .unwrap_or(0);
-        self.value.remove(prev_boundary);
-        self.cursor = prev_boundary;
     }
 
     /// Delete the grapheme cluster at the cursor (delete key)
     ///
     /// Deletes the entire grapheme cluster, handling combining characters properly.
     pub fn delete(&mut self) {
-        if !self.is_enabled() || self.cursor >= self.value.len() {
-            return;
+        if self.is_enabled() {
+            self.edit.delete();
         }
-        let next_boundary = grapheme::next_grapheme_boundary(&self.value, self.cursor);
-        self.value.drain(self.cursor..next_boundary);
     }
 
     /// Move cursor left (to previous grapheme cluster boundary)
@@ -138,9 +130,7 @@ impl TextInputState {
     /// Uses grapheme cluster boundaries for proper handling of combining characters
     /// like Thai diacritics, emoji with modifiers, etc.
     pub fn move_left(&mut self) {
-        if self.cursor > 0 {
-            self.cursor = grapheme::prev_grapheme_boundary(&self.value, self.cursor);
-        }
+        self.edit.move_left();
     }
 
     /// Move cursor right (to next grapheme cluster boundary)
@@ -148,34 +138,30 @@ impl TextInputState {
     /// Uses grapheme cluster boundaries for proper handling of combining characters
     /// like Thai diacritics, emoji with modifiers, etc.
     pub fn move_right(&mut self) {
-        if self.cursor < self.value.len() {
-            self.cursor = grapheme::next_grapheme_boundary(&self.value, self.cursor);
-        }
+        self.edit.move_right();
     }
 
     /// Move cursor to start
     pub fn move_home(&mut self) {
-        self.cursor = 0;
+        self.edit.move_home();
     }
 
     /// Move cursor to end
     pub fn move_end(&mut self) {
-        self.cursor = self.value.len();
+        self.edit.move_end();
     }
 
     /// Clear the input
     pub fn clear(&mut self) {
         if self.is_enabled() {
-            self.value.clear();
-            self.cursor = 0;
+            self.edit.clear();
         }
     }
 
     /// Set the value directly
     pub fn set_value(&mut self, value: impl Into<String>) {
         if self.is_enabled() {
-            self.value = value.into();
-            self.cursor = self.value.len();
+            self.edit.set_value(value);
         }
     }
 }
@@ -193,6 +179,8 @@ pub struct TextInputColors {
     pub placeholder: Color,
     /// Cursor color
     pub cursor: Color,
+    /// Selected text background
+    pub selection: Color,
     /// Focused highlight color
     pub focused: Color,
     /// Disabled color
@@ -207,6 +195,7 @@ impl Default for TextInputColors {
             border: Color::Gray,
             placeholder: Color::DarkGray,
             cursor: Color::Yellow,
+            selection: Color::DarkGray,
             focused: Color::Cyan,
             disabled: Color::DarkGray,
         }
@@ -222,6 +211,7 @@ impl TextInputColors {
             border: theme.line_number_fg,
             placeholder: theme.line_number_fg,
             cursor: theme.cursor,
+            selection: theme.selection_bg,
             focused: theme.selection_bg,
             disabled: theme.line_number_fg,
         }
@@ -294,34 +284,34 @@ mod tests {
         state.insert('a');
         state.insert('b');
         state.insert('c');
-        assert_eq!(state.value, "abc");
-        assert_eq!(state.cursor, 3);
+        assert_eq!(state.value(), "abc");
+        assert_eq!(state.cursor(), 3);
     }
 
     #[test]
     fn test_text_input_backspace() {
         let mut state = TextInputState::new("Test").with_value("abc");
         state.backspace();
-        assert_eq!(state.value, "ab");
-        assert_eq!(state.cursor, 2);
+        assert_eq!(state.value(), "ab");
+        assert_eq!(state.cursor(), 2);
     }
 
     #[test]
     fn test_text_input_cursor_movement() {
         let mut state = TextInputState::new("Test").with_value("hello");
-        assert_eq!(state.cursor, 5);
+        assert_eq!(state.cursor(), 5);
 
         state.move_left();
-        assert_eq!(state.cursor, 4);
+        assert_eq!(state.cursor(), 4);
 
         state.move_home();
-        assert_eq!(state.cursor, 0);
+        assert_eq!(state.cursor(), 0);
 
         state.move_right();
-        assert_eq!(state.cursor, 1);
+        assert_eq!(state.cursor(), 1);
 
         state.move_end();
-        assert_eq!(state.cursor, 5);
+        assert_eq!(state.cursor(), 5);
     }
 
     #[test]
@@ -329,23 +319,23 @@ mod tests {
         let mut state = TextInputState::new("Test").with_value("abc");
         state.move_home();
         state.delete();
-        assert_eq!(state.value, "bc");
-        assert_eq!(state.cursor, 0);
+        assert_eq!(state.value(), "bc");
+        assert_eq!(state.cursor(), 0);
     }
 
     #[test]
     fn test_text_input_disabled() {
         let mut state = TextInputState::new("Test").with_focus(FocusState::Disabled);
         state.insert('a');
-        assert_eq!(state.value, "");
+        assert_eq!(state.value(), "");
     }
 
     #[test]
     fn test_text_input_clear() {
         let mut state = TextInputState::new("Test").with_value("hello");
         state.clear();
-        assert_eq!(state.value, "");
-        assert_eq!(state.cursor, 0);
+        assert_eq!(state.value(), "");
+        assert_eq!(state.cursor(), 0);
     }
 
     #[test]
@@ -354,50 +344,50 @@ mod tests {
         let mut state = TextInputState::new("Test");
         // © is 2 bytes in UTF-8
         state.insert('©');
-        assert_eq!(state.value, "©");
-        assert_eq!(state.cursor, 2); // byte position, not char position
+        assert_eq!(state.value(), "©");
+        assert_eq!(state.cursor(), 2); // byte position, not char position
 
         // Backspace should delete the whole character, not cause a panic
         state.backspace();
-        assert_eq!(state.value, "");
-        assert_eq!(state.cursor, 0);
+        assert_eq!(state.value(), "");
+        assert_eq!(state.cursor(), 0);
     }
 
     #[test]
     fn test_text_input_multibyte_cursor_movement() {
         let mut state = TextInputState::new("Test").with_value("日本語");
         // Each Japanese character is 3 bytes
-        assert_eq!(state.cursor, 9);
+        assert_eq!(state.cursor(), 9);
 
         state.move_left();
-        assert_eq!(state.cursor, 6); // moved back by one character (3 bytes)
+        assert_eq!(state.cursor(), 6); // moved back by one character (3 bytes)
 
         state.move_left();
-        assert_eq!(state.cursor, 3);
+        assert_eq!(state.cursor(), 3);
 
         state.move_right();
-        assert_eq!(state.cursor, 6);
+        assert_eq!(state.cursor(), 6);
 
         state.move_home();
-        assert_eq!(state.cursor, 0);
+        assert_eq!(state.cursor(), 0);
 
         state.move_right();
-        assert_eq!(state.cursor, 3); // moved forward by one character (3 bytes)
+        assert_eq!(state.cursor(), 3); // moved forward by one character (3 bytes)
     }
 
     #[test]
     fn test_text_input_multibyte_delete() {
         let mut state = TextInputState::new("Test").with_value("a日b");
         // 'a' is 1 byte, '日' is 3 bytes, 'b' is 1 byte = 5 bytes total
-        assert_eq!(state.cursor, 5);
+        assert_eq!(state.cursor(), 5);
 
         state.move_home();
         state.move_right(); // cursor now at byte 1 (after 'a', before '日')
-        assert_eq!(state.cursor, 1);
+        assert_eq!(state.cursor(), 1);
 
         state.delete(); // delete '日'
-        assert_eq!(state.value, "ab");
-        assert_eq!(state.cursor, 1);
+        assert_eq!(state.value(), "ab");
+        assert_eq!(state.cursor(), 1);
     }
 
     #[test]
@@ -405,10 +395,10 @@ mod tests {
         let mut state = TextInputState::new("Test").with_value("日語");
         state.move_home();
         state.move_right(); // cursor after first character
-        assert_eq!(state.cursor, 3);
+        assert_eq!(state.cursor(), 3);
 
         state.insert('本');
-        assert_eq!(state.value, "日本語");
-        assert_eq!(state.cursor, 6);
+        assert_eq!(state.value(), "日本語");
+        assert_eq!(state.cursor(), 6);
     }
 }
@@ -80,55 +80,47 @@ pub fn render_text_input_aligned(
     let final_label_width = actual_label_width + 2;
     let actual_field_width = field_width.min(area.width.saturating_sub(final_label_width + 2));
 
-    let (display_text, is_placeholder) = if state.value.is_empty() && !state.placeholder.is_empty()
-    {
-        (&state.placeholder, true)
-    } else {
-        (&state.value, false)
-    };
-
+    let is_placeholder = state.value().is_empty() && !state.placeholder.is_empty();
     let inner_width = actual_field_width.saturating_sub(2) as usize;
 
-    // Calculate visual width of text before cursor for proper scrolling
-    // state.cursor is a byte offset, we need the visual width
-    let text_before_cursor = &state.value[..state.cursor.min(state.value.len())];
-    let cursor_visual_pos = str_width(text_before_cursor);
-
-    // Calculate scroll offset based on visual width
-    let scroll_visual_offset = if cursor_visual_pos > inner_width {
-        cursor_visual_pos - inner_width
+    // Scrolls horizontally to keep the cursor visible
+    let visible = state.edit.visible(inner_width);
+
+    let text_style = Style::default().fg(text_color);
+    let selection_style = text_style.bg(colors.selection);
+    let mut text_spans: Vec<Span> = if is_placeholder {
+        let placeholder: String = state
+            .placeholder
+            .chars()
+            .scan(0, |width, ch| {
+                *width += char_width(ch);
+                (*width <= inner_width).then_some(ch)
+            })
+            .collect();
+        vec![Span::styled(
+            placeholder,
+            Style::default().fg(placeholder_color),
+        )]
     } else {
-        0
+        visible
+            .runs
+            .into_iter()
+            .map(|(text, selected)| {
+                let style = if selected && state.focus == FocusState::Focused {
+                    selection_style
+                } else {
+                    text_style
+                };
+                Span::styled(text, style)
+            })
+            .collect()
     };
 
-    // Build visible text by iterating chars and tracking visual width
-    let mut visible_text = String::new();
-    let mut current_visual_pos = 0;
-    for ch in display_text.chars() {
-        let ch_width = char_width(ch);
-        // Skip characters before scroll offset
-        if current_visual_pos + ch_width <= scroll_visual_offset {
-            current_visual_pos += ch_width;
-            continue;
-        }
-        // Stop if we've filled the visible area
-        if current_visual_pos - scroll_visual_offset >= inner_width {
-            break;
-        }
-        visible_text.push(ch);
-        current_visual_pos += ch_width;
-    }
-
     // Pad to fill the field width
-    let visible_width = str_width(&visible_text);
-    let padding = " ".repeat(inner_width.saturating_sub(visible_width));
-    let padded = format!("{}{}", visible_text, padding);
-
-    let text_style = if is_placeholder {
-        Style::default().fg(placeholder_color)
-    } else {
-        Style::default().fg(text_color)
-    };
+    let visible_width: usize = text_spans.iter().map(|span| str_width(&span.content)).sum();
+    text_spans.push(Span::raw(
+        " ".repeat(inner_width.saturating_sub(visible_width)),
+    ));
 
     let padded_label = format!(
         "{:width$}",
@@ -136,13 +128,14 @@ pub fn render_text_input_aligned(
         width = actual_label_width as usize
     );
 
-    let line = Line::from(vec![
+    let mut spans = vec![
         Span::styled(padded_label, Style::default().fg(label_color)),
         Span::styled(": ", Style::default().fg(label_color)),
         Span::styled("[", Style::default().fg(border_color)),
-        Span::styled(padded, text_style),
-        Span::styled("]", Style::default().fg(border_color)),
-    ]);
+    ];
+    spans.extend(text_spans);
+    spans.push(Span::styled("]", Style::default().fg(border_color)));
+    let line = Line::from(spans);
 
     let paragraph = Paragraph::new(line);
     frame.render_widget(paragraph, area);
@@ -151,14 +144,12 @@ pub fn render_text_input_aligned(
     let input_area = Rect::new(input_start, area.y, actual_field_width + 2, 1);
 
     let cursor_pos = if state.focus == FocusState::Focused && !is_placeholder {
-        // Calculate cursor visual position within the visible area
-        let cursor_visual_in_field = cursor_visual_pos.saturating_sub(scroll_visual_offset);
-        let cursor_x = input_start + 1 + cursor_visual_in_field as u16;
+        let cursor_x = input_start + 1 + visible.cursor_col as u16;
         if cursor_x < input_start + actual_field_width + 1 {
             let cursor_area = Rect::new(cursor_x, area.y, 1, 1);
             // Get the grapheme at cursor position for the highlight
-            let cursor_char = if state.cursor < state.value.len() {
-                crate::primitives::grapheme::grapheme_at(&state.value, state.cursor)
+            let cursor_char = if state.cursor() < state.value().len() {
+                crate::primitives::grapheme::grapheme_at(state.value(), state.cursor())
                     .map(|(g, _, _)| g.chars().next().unwrap_or(' '))
                     .unwrap_or(' ')
             } else {
@@ -8,6 +8,8 @@ use ratatui::Frame;
 
 use super::{FocusState, TextListColors, TextListLayout, TextListRowLayout, TextListState};
 
+use crate::view::glyphs::glyphs;
+
 /// Render a text list control
 ///
 /// # Arguments
@@ -75,7 +77,10 @@ pub fn render_text_list(
             Span::styled(padded, Style::default().fg(text_color)),
             Span::styled("]", Style::default().fg(border_color)),
             Span::raw(" "),
-            Span::styled("[x]", Style::default().fg(colors.remove_button)),
+            Span::styled(
+                glyphs().remove_button,
+                Style::default().fg(colors.remove_button),
+            ),
         ]);
 
         let row_area = Rect::new(area.x, y, area.width, 1);
@@ -123,7 +128,7 @@ pub fn render_text_list(
             Span::styled(padded, Style::default().fg(text_color)),
             Span::styled("]", Style::default().fg(border_color)),
             Span::raw(" "),
-            Span::styled("[+]", Style::default().fg(colors.add_button)),
+            Span::styled(glyphs().add_button, Style::default().fg(colors.add_button)),
         ]);
 
         let row_area = Rect::new(area.x, y, area.width, 1);
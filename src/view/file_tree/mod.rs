@@ -11,5 +11,5 @@ pub mod view;
 
 pub use ignore::{IgnorePatterns, IgnoreStatus};
 pub use node::{NodeId, NodeState, TreeNode};
-pub use tree::FileTree;
-pub use view::{FileTreeView, SortMode};
+pub use tree::{FileTree, SortMode, TreeRow, TreeSort};
+pub use view::FileTreeView;
@@ -24,6 +24,8 @@ pub struct TreeNode {
     pub children: Vec<NodeId>,
     /// Current state of the node
     pub state: NodeState,
+    /// Show every child even past the tree's display cap
+    pub show_all_children: bool,
 }
 
 impl TreeNode {
@@ -41,6 +43,7 @@ impl TreeNode {
             parent,
             children: Vec::new(),
             state,
+            show_all_children: false,
         }
     }
 
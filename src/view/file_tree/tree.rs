@@ -1,5 +1,6 @@
 use super::node::{NodeId, NodeState, TreeNode};
-use crate::services::fs::{FsEntry, FsManager};
+use crate::services::fs::{FsEntry, FsManager, FsMetadata};
+use std::cmp::Ordering;
 use std::collections::HashMap;
 use std::io;
 use std::path::{Path, PathBuf};
@@ -24,6 +25,74 @@ pub struct FileTree {
     next_id: usize,
     /// Filesystem manager for async operations
     fs_manager: Arc<FsManager>,
+    /// Maximum children shown per directory (0 = no limit)
+    max_visible_children: usize,
+    /// Order of the entries of each directory
+    sort: TreeSort,
+}
+
+/// What the entries of a directory are sorted by
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SortMode {
+    /// Case-insensitive name
+    #[default]
+    Name,
+    /// Modification time
+    Modified,
+    /// File size (directories keep their name order)
+    Size,
+}
+
+/// Order of the entries of each directory
+///
+/// Directories always come before files. Entries whose metadata isn't known
+/// yet go last, and ties are broken by name.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct TreeSort {
+    pub mode: SortMode,
+    pub descending: bool,
+}
+
+impl TreeSort {
+    /// The sort after this one when cycling: name A-Z and Z-A, newest and
+    /// oldest first, largest and smallest first
+    pub fn next(self) -> Self {
+        let (mode, descending) = match (self.mode, self.descending) {
+            (SortMode::Name, false) => (SortMode::Name, true),
+            (SortMode::Name, true) => (SortMode::Modified, true),
+            (SortMode::Modified, true) => (SortMode::Modified, false),
+            (SortMode::Modified, false) => (SortMode::Size, true),
+            (SortMode::Size, true) => (SortMode::Size, false),
+            (SortMode::Size, false) => (SortMode::Name, false),
+        };
+        Self { mode, descending }
+    }
+
+    /// Whether entries need their metadata to be put in this order
+    pub fn uses_metadata(self) -> bool {
+        self.mode != SortMode::Name
+    }
+}
+
+/// A row of the flattened tree as displayed in the explorer
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TreeRow {
+    /// A file or directory
+    Node(NodeId),
+    /// Footer under a directory whose entries are still being read
+    Loading(NodeId),
+    /// Footer under a directory with more entries than the display cap
+    MoreEntries { dir: NodeId, hidden: usize },
+}
+
+impl TreeRow {
+    /// The node shown on this row, if it isn't a footer
+    pub fn node_id(&self) -> Option<NodeId> {
+        match self {
+            TreeRow::Node(id) => Some(*id),
+            _ => None,
+        }
+    }
 }
 
 impl FileTree {
@@ -68,6 +137,8 @@ impl FileTree {
             root_id,
             next_id: 1,
             fs_manager,
+            max_visible_children: 0,
+            sort: TreeSort::default(),
         })
     }
 
@@ -112,7 +183,31 @@ impl FileTree {
     ///
     /// Returns an error if the directory cannot be read.
     pub async fn expand_node(&mut self, id: NodeId) -> io::Result<()> {
-        // Check if node exists and is a directory
+        if self.get_node(id).is_some_and(|node| node.is_expanded()) {
+            return Ok(());
+        }
+
+        // Read directory contents with metadata (for file sizes)
+        let path = self.begin_expand(id)?;
+        match self.fs_manager.list_dir_with_metadata(path).await {
+            Ok(entries) => {
+                self.append_children(id, entries);
+                self.finish_expand(id);
+                Ok(())
+            }
+            Err(e) => {
+                self.fail_expand(id, &e);
+                Err(e)
+            }
+        }
+    }
+
+    /// Start expanding a directory whose entries will arrive in batches
+    ///
+    /// Any existing children are dropped and the node is put in the loading
+    /// state; feed entries with `append_children()` and end with
+    /// `finish_expand()` or `fail_expand()`. Returns the directory's path.
+    pub fn begin_expand(&mut self, id: NodeId) -> io::Result<PathBuf> {
         let node = self
             .get_node(id)
             .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "Node not found"))?;
@@ -123,56 +218,72 @@ impl FileTree {
                 "Cannot expand a file node",
             ));
         }
+        let path = node.entry.path.clone();
 
-        // If already expanded, do nothing
-        if node.is_expanded() {
-            return Ok(());
-        }
-
-        // Set state to loading
+        self.collapse_node(id);
         if let Some(node) = self.get_node_mut(id) {
             node.state = NodeState::Loading;
+            node.show_all_children = false;
         }
+        Ok(path)
+    }
 
-        // Read directory contents with metadata (for file sizes)
-        let path = self.get_node(id).unwrap().entry.path.clone();
-        let result = self.fs_manager.list_dir_with_metadata(path.clone()).await;
-
-        match result {
-            Ok(entries) => {
-                // Sort entries: directories first, then by name
-                let mut sorted_entries = entries;
-                sorted_entries.sort_by(|a, b| match (a.is_dir(), b.is_dir()) {
-                    (true, false) => std::cmp::Ordering::Less,
-                    (false, true) => std::cmp::Ordering::Greater,
-                    _ => a.name.to_lowercase().cmp(&b.name.to_lowercase()),
-                });
-
-                // Create child nodes
-                let mut child_ids = Vec::new();
-                for entry in sorted_entries {
-                    let child_id = self.add_node(entry, Some(id));
-                    child_ids.push(child_id);
+    /// Add a batch of entries to a directory, keeping its children sorted
+    ///
+    /// Entries already in the tree are skipped. Existing node IDs are kept,
+    /// so a selection inside the directory survives later batches.
+    pub fn append_children(&mut self, id: NodeId, entries: Vec<FsEntry>) {
+        let Some(node) = self.get_node_mut(id) else {
+            return;
+        };
+        let existing = std::mem::take(&mut node.children);
+
+        let mut entries: Vec<FsEntry> = entries
+            .into_iter()
+            .filter(|entry| !self.path_to_node.contains_key(&entry.path))
+            .collect();
+        let sort = self.sort;
+        entries.sort_by(|a, b| compare_entries(a, b, sort));
+
+        // Merge the sorted batch into the already sorted children
+        let mut merged = Vec::with_capacity(existing.len() + entries.len());
+        let mut existing = existing.into_iter().peekable();
+        for entry in entries {
+            while let Some(&child_id) = existing.peek() {
+                let child = &self.nodes[&child_id].entry;
+                if compare_entries(child, &entry, sort) == Ordering::Greater {
+                    break;
                 }
+                merged.push(child_id);
+                existing.next();
+            }
+            merged.push(self.add_node(entry, Some(id)));
+        }
+        merged.extend(existing);
 
-                // Update parent node
-                if let Some(node) = self.get_node_mut(id) {
-                    node.children = child_ids;
-                    node.state = NodeState::Expanded;
-                }
+        if let Some(node) = self.get_node_mut(id) {
+            node.children = merged;
+        }
+    }
 
-                Ok(())
-            }
-            Err(e) => {
-                // Set error state
-                if let Some(node) = self.get_node_mut(id) {
-                    node.state = NodeState::Error(e.to_string());
-                }
-                Err(e)
+    /// Mark a directory loaded with `begin_expand()` as fully read
+    pub fn finish_expand(&mut self, id: NodeId) {
+        if let Some(node) = self.get_node_mut(id) {
+            if node.is_loading() {
+                node.state = NodeState::Expanded;
             }
         }
     }
 
+    /// Mark a directory loaded with `begin_expand()` as failed, dropping
+    /// any entries read so far
+    pub fn fail_expand(&mut self, id: NodeId, error: &io::Error) {
+        self.collapse_node(id);
+        if let Some(node) = self.get_node_mut(id) {
+            node.state = NodeState::Error(error.to_string());
+        }
+    }
+
     /// Collapse a directory node
     ///
     /// This removes all child nodes from memory to save space.
@@ -226,29 +337,216 @@ impl FileTree {
         self.expand_node(id).await
     }
 
+    /// Add an entry to a loaded directory, or update the node with its path
+    ///
+    /// An updated node keeps its ID and, for a directory, what's loaded
+    /// under it; one that changed between file and directory is replaced.
+    /// Returns true if the tree changed.
+    pub fn upsert_child(&mut self, dir: NodeId, entry: FsEntry) -> bool {
+        if let Some(&id) = self.path_to_node.get(&entry.path) {
+            if id == self.root_id {
+                return false;
+            }
+            let node = &self.nodes[&id];
+            if node.parent != Some(dir) || node.is_dir() != entry.is_dir() {
+                self.remove_entry(&entry.path);
+                return self.upsert_child(dir, entry);
+            }
+            if same_metadata(&node.entry, &entry) {
+                return false;
+            }
+            if let Some(node) = self.get_node_mut(id) {
+                node.entry = entry;
+            }
+            if self.sort.uses_metadata() {
+                self.reposition_child(dir, id);
+            }
+            return true;
+        }
+
+        if self.get_node(dir).is_none() {
+            return false;
+        }
+        let id = self.add_node(entry, Some(dir));
+        self.reposition_child(dir, id);
+        true
+    }
+
+    /// Move a child of `dir` to where it sorts among the others
+    fn reposition_child(&mut self, dir: NodeId, id: NodeId) {
+        let Some(mut children) = self
+            .get_node_mut(dir)
+            .map(|node| std::mem::take(&mut node.children))
+        else {
+            return;
+        };
+        children.retain(|&child| child != id);
+        let entry = &self.nodes[&id].entry;
+        let index = children.partition_point(|child| {
+            compare_entries(&self.nodes[child].entry, entry, self.sort).is_lt()
+        });
+        children.insert(index, id);
+        if let Some(node) = self.get_node_mut(dir) {
+            node.children = children;
+        }
+    }
+
+    /// Get the order of the entries of each directory
+    pub fn sort(&self) -> TreeSort {
+        self.sort
+    }
+
+    /// Set the order of the entries of each directory, re-sorting the
+    /// loaded ones
+    pub fn set_sort(&mut self, sort: TreeSort) {
+        self.sort = sort;
+        let dirs: Vec<NodeId> = self
+            .nodes
+            .values()
+            .filter(|node| node.children.len() > 1)
+            .map(|node| node.id)
+            .collect();
+        for dir in dirs {
+            self.sort_children(dir);
+        }
+    }
+
+    /// Put the children of a directory back in order, after their metadata
+    /// changed
+    pub fn sort_children(&mut self, dir: NodeId) {
+        let Some(mut children) = self
+            .get_node_mut(dir)
+            .map(|node| std::mem::take(&mut node.children))
+        else {
+            return;
+        };
+        children
+            .sort_by(|a, b| compare_entries(&self.nodes[a].entry, &self.nodes[b].entry, self.sort));
+        if let Some(node) = self.get_node_mut(dir) {
+            node.children = children;
+        }
+    }
+
+    /// Set the metadata of the entry at `path`, leaving it where it is
+    ///
+    /// Returns the entry's directory, to be re-sorted with
+    /// `sort_children()` if the sort uses metadata.
+    pub fn set_metadata(&mut self, path: &Path, metadata: FsMetadata) -> Option<NodeId> {
+        let id = *self.path_to_node.get(path)?;
+        let node = self.get_node_mut(id)?;
+        node.entry.metadata = Some(metadata);
+        node.parent
+    }
+
+    /// Remove an entry and everything loaded under it
+    ///
+    /// Returns true if the entry was in the tree. The root can't be removed.
+    pub fn remove_entry(&mut self, path: &Path) -> bool {
+        let Some(&id) = self.path_to_node.get(path) else {
+            return false;
+        };
+        let Some(parent) = self.nodes[&id].parent else {
+            return false;
+        };
+        if let Some(parent) = self.get_node_mut(parent) {
+            parent.children.retain(|&child| child != id);
+        }
+        self.remove_node_recursive(id);
+        true
+    }
+
+    /// The entry to select in place of one about to be removed: the next
+    /// entry of its directory, else the previous one, else the directory
+    pub fn nearest_sibling(&self, id: NodeId) -> Option<NodeId> {
+        let parent = self.get_node(id)?.parent?;
+        let siblings = &self.get_node(parent)?.children;
+        let index = siblings.iter().position(|&child| child == id)?;
+        siblings
+            .get(index + 1)
+            .or_else(|| index.checked_sub(1).and_then(|prev| siblings.get(prev)))
+            .copied()
+            .or(Some(parent))
+    }
+
     /// Get all visible nodes in tree order
     ///
     /// Returns a flat list of nodes that should be visible, respecting
     /// the expansion state of parent directories.
     pub fn get_visible_nodes(&self) -> Vec<NodeId> {
+        self.get_visible_rows()
+            .into_iter()
+            .filter_map(|row| row.node_id())
+            .collect()
+    }
+
+    /// Get all visible rows in tree order, including loading and
+    /// "more entries" footers under directories
+    pub fn get_visible_rows(&self) -> Vec<TreeRow> {
         let mut visible = Vec::new();
         self.collect_visible_recursive(self.root_id, &mut visible);
         visible
     }
 
-    /// Recursively collect visible nodes
-    fn collect_visible_recursive(&self, id: NodeId, visible: &mut Vec<NodeId>) {
-        visible.push(id);
+    /// Recursively collect visible rows
+    fn collect_visible_recursive(&self, id: NodeId, visible: &mut Vec<TreeRow>) {
+        visible.push(TreeRow::Node(id));
 
         if let Some(node) = self.get_node(id) {
-            if node.is_expanded() {
-                for &child_id in &node.children {
+            // Entries of a directory still being read are shown as they arrive
+            if node.is_expanded() || node.is_loading() {
+                for &child_id in self.visible_children(id) {
                     self.collect_visible_recursive(child_id, visible);
                 }
+                let hidden = self.hidden_child_count(id);
+                if hidden > 0 {
+                    visible.push(TreeRow::MoreEntries { dir: id, hidden });
+                }
+                if node.is_loading() {
+                    visible.push(TreeRow::Loading(id));
+                }
             }
         }
     }
 
+    /// Set the maximum number of children shown per directory (0 = no limit)
+    pub fn set_max_visible_children(&mut self, max: usize) {
+        self.max_visible_children = max;
+    }
+
+    /// Get the maximum number of children shown per directory (0 = no limit)
+    pub fn max_visible_children(&self) -> usize {
+        self.max_visible_children
+    }
+
+    /// Children of a directory that fit under the display cap
+    pub fn visible_children(&self, id: NodeId) -> &[NodeId] {
+        let Some(node) = self.get_node(id) else {
+            return &[];
+        };
+        if node.show_all_children || self.max_visible_children == 0 {
+            &node.children
+        } else {
+            &node.children[..node.children.len().min(self.max_visible_children)]
+        }
+    }
+
+    /// Number of loaded children of a directory hidden by the display cap
+    pub fn hidden_child_count(&self, id: NodeId) -> usize {
+        let total = self.get_node(id).map_or(0, |node| node.children.len());
+        total - self.visible_children(id).len()
+    }
+
+    /// Lift the display cap for a directory
+    ///
+    /// Returns true if any hidden children became visible.
+    pub fn show_all_children(&mut self, id: NodeId) -> bool {
+        let hidden = self.hidden_child_count(id);
+        if let Some(node) = self.get_node_mut(id) {
+            node.show_all_children = true;
+        }
+        hidden > 0
+    }
+
     /// Get the parent chain for a node (from root to node)
     pub fn get_ancestors(&self, id: NodeId) -> Vec<NodeId> {
         let mut ancestors = Vec::new();
@@ -385,10 +683,52 @@ impl FileTree {
     }
 }
 
+/// Whether two entries of the same path have the same size and mtime
+fn same_metadata(a: &FsEntry, b: &FsEntry) -> bool {
+    match (&a.metadata, &b.metadata) {
+        (Some(a), Some(b)) => a.size == b.size && a.modified == b.modified,
+        (None, None) => true,
+        _ => false,
+    }
+}
+
+/// Order entries with directories first, then as `sort` says
+fn compare_entries(a: &FsEntry, b: &FsEntry, sort: TreeSort) -> Ordering {
+    match (a.is_dir(), b.is_dir()) {
+        (true, false) => return Ordering::Less,
+        (false, true) => return Ordering::Greater,
+        _ => {}
+    }
+    let by_name = || a.name.to_lowercase().cmp(&b.name.to_lowercase());
+    let modified = |entry: &FsEntry| entry.metadata.as_ref().and_then(|m| m.modified);
+    let size = |entry: &FsEntry| entry.metadata.as_ref().and_then(|m| m.size);
+    let order = match sort.mode {
+        SortMode::Name if sort.descending => by_name().reverse(),
+        SortMode::Name => by_name(),
+        SortMode::Modified => known_first(modified(a), modified(b), sort.descending),
+        // A directory's size says nothing about what's in it
+        SortMode::Size if a.is_dir() => Ordering::Equal,
+        SortMode::Size => known_first(size(a), size(b), sort.descending),
+    };
+    order.then_with(by_name)
+}
+
+/// Order of two values that may not be known yet; unknown ones go last
+/// either way
+fn known_first<T: Ord>(a: Option<T>, b: Option<T>, descending: bool) -> Ordering {
+    match (a, b) {
+        (Some(a), Some(b)) if descending => b.cmp(&a),
+        (Some(a), Some(b)) => a.cmp(&b),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::services::fs::LocalFsBackend;
+    use crate::services::fs::{FsEntryType, LocalFsBackend};
     use std::fs as std_fs;
     use tempfile::TempDir;
 
@@ -722,4 +1062,209 @@ mod tests {
 
         assert!(result.is_none(), "Should return None for nonexistent paths");
     }
+
+    fn file_entry(dir: &Path, name: &str) -> FsEntry {
+        FsEntry::new(dir.join(name), name.to_string(), FsEntryType::File)
+    }
+
+    #[tokio::test]
+    async fn test_append_children_keeps_sorted_order() {
+        let (_temp_dir, mut tree) = create_test_tree().await;
+        let root_id = tree.root_id();
+        let root_path = tree.begin_expand(root_id).unwrap();
+        assert!(tree.get_node(root_id).unwrap().is_loading());
+
+        tree.append_children(
+            root_id,
+            vec![
+                file_entry(&root_path, "d.txt"),
+                file_entry(&root_path, "B.txt"),
+            ],
+        );
+        let b_id = tree.get_node(root_id).unwrap().children[0];
+        tree.append_children(
+            root_id,
+            vec![
+                file_entry(&root_path, "c.txt"),
+                file_entry(&root_path, "a.txt"),
+                FsEntry::new(root_path.join("z"), "z".to_string(), FsEntryType::Directory),
+                // Already present entries are ignored
+                file_entry(&root_path, "d.txt"),
+            ],
+        );
+        tree.finish_expand(root_id);
+
+        let names: Vec<&str> = tree
+            .get_node(root_id)
+            .unwrap()
+            .children
+            .iter()
+            .map(|id| tree.get_node(*id).unwrap().entry.name.as_str())
+            .collect();
+        assert_eq!(names, ["z", "a.txt", "B.txt", "c.txt", "d.txt"]);
+        assert!(tree.get_node(root_id).unwrap().is_expanded());
+        // Earlier nodes keep their IDs
+        assert_eq!(tree.get_node(b_id).unwrap().entry.name, "B.txt");
+    }
+
+    #[tokio::test]
+    async fn test_upsert_and_remove_entries_in_place() {
+        let (temp_dir, mut tree) = create_test_tree().await;
+        let root_id = tree.root_id();
+        tree.expand_node(root_id).await.unwrap();
+        let dir1_id = tree.get_node(root_id).unwrap().children[0];
+        tree.expand_node(dir1_id).await.unwrap();
+        let dir1 = temp_dir.path().join("dir1");
+        let file1_id = tree.get_node_by_path(&dir1.join("file1.txt")).unwrap().id;
+
+        // New entries go where they sort; the loaded directory keeps its children
+        assert!(tree.upsert_child(root_id, file_entry(temp_dir.path(), "a.txt")));
+        assert!(!tree.upsert_child(root_id, file_entry(temp_dir.path(), "a.txt")));
+        let names: Vec<&str> = tree
+            .get_node(root_id)
+            .unwrap()
+            .children
+            .iter()
+            .map(|id| tree.get_node(*id).unwrap().entry.name.as_str())
+            .collect();
+        assert_eq!(names, ["dir1", "dir2", "a.txt", "file4.txt"]);
+        assert_eq!(tree.get_node(dir1_id).unwrap().children.len(), 2);
+
+        // Next sibling, else previous one, else the directory
+        assert_eq!(
+            tree.nearest_sibling(file1_id),
+            tree.get_node_by_path(&dir1.join("file2.txt")).map(|n| n.id)
+        );
+        assert!(tree.remove_entry(&dir1.join("file2.txt")));
+        assert_eq!(tree.nearest_sibling(file1_id), Some(dir1_id));
+
+        // Removing a directory drops everything loaded under it
+        let count = tree.node_count();
+        assert!(tree.remove_entry(&dir1));
+        assert_eq!(tree.node_count(), count - 2);
+        assert!(tree.get_node(file1_id).is_none());
+        assert!(!tree.remove_entry(&dir1));
+        assert!(!tree.remove_entry(temp_dir.path()));
+    }
+
+    #[tokio::test]
+    async fn test_loading_directory_rows() {
+        let (_temp_dir, mut tree) = create_test_tree().await;
+        let root_id = tree.root_id();
+        let root_path = tree.begin_expand(root_id).unwrap();
+        tree.append_children(root_id, vec![file_entry(&root_path, "a.txt")]);
+
+        let rows = tree.get_visible_rows();
+        assert_eq!(rows.len(), 3);
+        assert_eq!(rows[2], TreeRow::Loading(root_id));
+        assert_eq!(tree.get_visible_nodes().len(), 2);
+
+        tree.fail_expand(root_id, &io::Error::other("gone"));
+        assert!(tree.get_node(root_id).unwrap().is_error());
+        assert_eq!(tree.get_visible_rows(), vec![TreeRow::Node(root_id)]);
+    }
+
+    #[tokio::test]
+    async fn test_visible_children_cap() {
+        let (_temp_dir, mut tree) = create_test_tree().await;
+        tree.set_max_visible_children(2);
+        let root_id = tree.root_id();
+        tree.expand_node(root_id).await.unwrap();
+
+        let rows = tree.get_visible_rows();
+        assert_eq!(rows.len(), 4); // root + 2 children + footer
+        assert_eq!(
+            rows[3],
+            TreeRow::MoreEntries {
+                dir: root_id,
+                hidden: 1
+            }
+        );
+
+        assert!(tree.show_all_children(root_id));
+        assert_eq!(tree.hidden_child_count(root_id), 0);
+        assert_eq!(tree.get_visible_rows().len(), 4); // root + 3 children
+        assert!(!tree.show_all_children(root_id));
+    }
+
+    fn child_names(tree: &FileTree, id: NodeId) -> Vec<&str> {
+        tree.get_node(id)
+            .unwrap()
+            .children
+            .iter()
+            .map(|id| tree.get_node(*id).unwrap().entry.name.as_str())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_sort_by_metadata() {
+        let (_temp_dir, mut tree) = create_test_tree().await;
+        let root_id = tree.root_id();
+        let root_path = tree.begin_expand(root_id).unwrap();
+        let epoch = std::time::SystemTime::UNIX_EPOCH;
+        let entry = |name: &str, size: u64, secs: u64| {
+            file_entry(&root_path, name).with_metadata(
+                FsMetadata::new()
+                    .with_size(size)
+                    .with_modified(epoch + std::time::Duration::from_secs(secs)),
+            )
+        };
+        tree.append_children(
+            root_id,
+            vec![
+                entry("a.log", 300, 20),
+                entry("b.log", 100, 30),
+                entry("c.log", 200, 10),
+                file_entry(&root_path, "d.log"),
+                FsEntry::new(root_path.join("z"), "z".to_string(), FsEntryType::Directory),
+            ],
+        );
+        tree.finish_expand(root_id);
+
+        let sorted = |mode, descending| TreeSort { mode, descending };
+        let expected = [
+            (
+                sorted(SortMode::Name, true),
+                vec!["z", "d.log", "c.log", "b.log", "a.log"],
+            ),
+            // Unknown metadata goes last either way
+            (
+                sorted(SortMode::Modified, true),
+                vec!["z", "b.log", "a.log", "c.log", "d.log"],
+            ),
+            (
+                sorted(SortMode::Modified, false),
+                vec!["z", "c.log", "a.log", "b.log", "d.log"],
+            ),
+            (
+                sorted(SortMode::Size, true),
+                vec!["z", "a.log", "c.log", "b.log", "d.log"],
+            ),
+            (
+                sorted(SortMode::Size, false),
+                vec!["z", "b.log", "c.log", "a.log", "d.log"],
+            ),
+            (
+                TreeSort::default(),
+                vec!["z", "a.log", "b.log", "c.log", "d.log"],
+            ),
+        ];
+        let mut sort = TreeSort::default();
+        for (expected_sort, expected_names) in expected {
+            sort = sort.next();
+            tree.set_sort(sort);
+            assert_eq!(sort, expected_sort);
+            assert_eq!(child_names(&tree, root_id), expected_names);
+        }
+
+        // Metadata arriving later moves the entry once its directory is re-sorted
+        tree.set_sort(sorted(SortMode::Size, true));
+        let parent = tree.set_metadata(&root_path.join("d.log"), FsMetadata::new().with_size(1000));
+        assert_eq!(parent, Some(root_id));
+        tree.sort_children(root_id);
+        assert_eq!(child_names(&tree, root_id)[1], "d.log");
+        // Updated entries move right away
+        assert!(tree.upsert_child(root_id, entry("b.log", 5000, 30)));
+        assert_eq!(child_names(&tree, root_id)[1], "b.log");
+    }
 }
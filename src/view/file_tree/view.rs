@@ -1,7 +1,10 @@
 use super::ignore::IgnorePatterns;
 use super::node::NodeId;
-use super::tree::FileTree;
-use crate::services::fs::FsEntry;
+use super::tree::{FileTree, TreeRow, TreeSort};
+use crate::services::fs::{FsEntry, FsMetadata};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
 
 /// View state for file tree navigation and filtering
 #[derive(Debug)]
@@ -12,23 +15,16 @@ pub struct FileTreeView {
     selected_node: Option<NodeId>,
     /// Scroll offset (index into visible nodes)
     scroll_offset: usize,
-    /// Sort mode for entries
-    sort_mode: SortMode,
+    /// Whether size and modified time columns are shown
+    show_details: bool,
     /// Ignore patterns for filtering
     ignore_patterns: IgnorePatterns,
     /// Last known viewport height (for scrolling calculations)
     pub(crate) viewport_height: usize,
-}
-
-/// Sort mode for file tree entries
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub enum SortMode {
-    /// Sort by name alphabetically
-    Name,
-    /// Sort by type (directories first, then files)
-    Type,
-    /// Sort by modification time (newest first)
-    Modified,
+    /// Cached entry counts of directories with the directory's mtime when counted
+    entry_counts: HashMap<PathBuf, (usize, Option<SystemTime>)>,
+    /// Cached metadata of entries listed without it
+    entry_metadata: HashMap<PathBuf, FsMetadata>,
 }
 
 impl FileTreeView {
@@ -39,9 +35,11 @@ impl FileTreeView {
             tree,
             selected_node: Some(root_id),
             scroll_offset: 0,
-            sort_mode: SortMode::Type,
+            show_details: false,
             ignore_patterns: IgnorePatterns::new(),
             viewport_height: 10, // Default, will be updated during rendering
+            entry_counts: HashMap::new(),
+            entry_metadata: HashMap::new(),
         }
     }
 
@@ -60,20 +58,98 @@ impl FileTreeView {
         &mut self.tree
     }
 
-    /// Get currently visible nodes with their indent levels
+    /// Get currently visible rows with their indent levels
     ///
-    /// Returns a list of (NodeId, indent_level) tuples for rendering.
-    pub fn get_display_nodes(&self) -> Vec<(NodeId, usize)> {
-        let visible = self.tree.get_visible_nodes();
+    /// Returns a list of (TreeRow, indent_level) tuples for rendering.
+    /// Footer rows are indented like the entries of their directory.
+    pub fn get_display_rows(&self) -> Vec<(TreeRow, usize)> {
+        let visible = self.tree.get_visible_rows();
         visible
             .into_iter()
-            .map(|id| {
-                let depth = self.tree.get_depth(id);
-                (id, depth)
+            .map(|row| {
+                let depth = match row {
+                    TreeRow::Node(id) => self.tree.get_depth(id),
+                    TreeRow::Loading(dir) | TreeRow::MoreEntries { dir, .. } => {
+                        self.tree.get_depth(dir) + 1
+                    }
+                };
+                (row, depth)
             })
             .collect()
     }
 
+    /// Add a batch of entries to a directory being expanded
+    ///
+    /// The selected entry stays on the same screen row even when the new
+    /// entries sort above it. Entries without metadata get the cached one.
+    pub fn append_children(&mut self, dir: NodeId, mut entries: Vec<FsEntry>) {
+        for entry in entries.iter_mut().filter(|entry| entry.metadata.is_none()) {
+            entry.metadata = self.entry_metadata.get(&entry.path).cloned();
+        }
+        let before = self.get_selected_index();
+        self.tree.append_children(dir, entries);
+        if let (Some(before), Some(after)) = (before, self.get_selected_index()) {
+            if before >= self.scroll_offset {
+                self.scroll_offset += after - before;
+            }
+        }
+    }
+
+    /// Remove an entry from the tree
+    ///
+    /// A selection on the entry, or under it, moves to the entry's nearest
+    /// sibling. Returns true if the entry was in the tree.
+    pub fn remove_entry(&mut self, path: &Path) -> bool {
+        let Some(id) = self.tree.get_node_by_path(path).map(|node| node.id) else {
+            return false;
+        };
+        if let Some(selected) = self.selected_node {
+            if self.tree.get_ancestors(selected).contains(&id) {
+                self.selected_node = self.tree.nearest_sibling(id);
+            }
+        }
+        self.tree.remove_entry(path)
+    }
+
+    /// Bring a loaded directory in line with a fresh listing of it
+    ///
+    /// Entries are added, removed and updated in place, so the rest keep
+    /// their nodes. Returns the number of entries changed.
+    pub fn sync_children(&mut self, dir: NodeId, entries: Vec<FsEntry>) -> usize {
+        let listed: HashSet<&Path> = entries.iter().map(|entry| entry.path.as_path()).collect();
+        let gone: Vec<PathBuf> = self
+            .tree
+            .get_node(dir)
+            .map(|node| {
+                node.children
+                    .iter()
+                    .filter_map(|&child| self.tree.get_node(child))
+                    .map(|child| child.entry.path.clone())
+                    .filter(|path| !listed.contains(path.as_path()))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let mut changed = 0;
+        for path in gone {
+            if self.remove_entry(&path) {
+                changed += 1;
+            }
+        }
+        for entry in entries {
+            if self.tree.upsert_child(dir, entry) {
+                changed += 1;
+            }
+        }
+        changed
+    }
+
+    /// Keep the scroll offset within the rows left after entries were removed
+    pub fn clamp_scroll_offset(&mut self) {
+        let last = self.visible_count().saturating_sub(1);
+        self.scroll_offset = self.scroll_offset.min(last);
+    }
+
     /// Get the currently selected node ID
     pub fn get_selected(&self) -> Option<NodeId> {
         self.selected_node
@@ -174,25 +250,39 @@ impl FileTreeView {
             return;
         }
 
-        if let Some(selected) = self.selected_node {
-            let visible = self.tree.get_visible_nodes();
-            if let Some(pos) = visible.iter().position(|&id| id == selected) {
-                // Only scroll if cursor goes PAST the viewport edges
-                // This implements symmetric scrolling behavior
-
-                // If selection is above the visible area, scroll up
-                if pos < self.scroll_offset {
-                    self.scroll_offset = pos;
-                }
-                // If selection is below the visible area, scroll down
-                else if pos >= self.scroll_offset + self.viewport_height {
-                    self.scroll_offset = pos - self.viewport_height + 1;
-                }
-                // Otherwise, cursor is within viewport - don't scroll
+        if let Some((pos, end)) = self.selected_row_span() {
+            // Only scroll if cursor goes PAST the viewport edges
+            // This implements symmetric scrolling behavior
+
+            // If selection is above the visible area, scroll up
+            if pos < self.scroll_offset {
+                self.scroll_offset = pos;
             }
+            // If selection is below the visible area, scroll down
+            else if end >= self.scroll_offset + self.viewport_height {
+                self.scroll_offset = (end + 1 - self.viewport_height).min(pos);
+            }
+            // Otherwise, cursor is within viewport - don't scroll
         }
     }
 
+    /// Row of the selected node and the last of the footer rows right below it
+    ///
+    /// Footers ("loading…", "N more not shown") can't be selected, so they are
+    /// scrolled into view together with the entry above them.
+    fn selected_row_span(&self) -> Option<(usize, usize)> {
+        let selected = self.selected_node?;
+        let rows = self.tree.get_visible_rows();
+        let pos = rows
+            .iter()
+            .position(|row| row.node_id() == Some(selected))?;
+        let footers = rows[pos + 1..]
+            .iter()
+            .take_while(|row| row.node_id().is_none())
+            .count();
+        Some((pos, pos + footers))
+    }
+
     /// Select the first visible node
     pub fn select_first(&mut self) {
         let visible = self.tree.get_visible_nodes();
@@ -242,30 +332,42 @@ impl FileTreeView {
             return;
         }
 
-        if let Some(selected) = self.selected_node {
-            let visible = self.tree.get_visible_nodes();
-            if let Some(pos) = visible.iter().position(|&id| id == selected) {
-                // If selection is above viewport, scroll up
-                if pos < self.scroll_offset {
-                    self.scroll_offset = pos;
-                }
-                // If selection is below viewport, scroll down
-                else if pos >= self.scroll_offset + viewport_height {
-                    self.scroll_offset = pos - viewport_height + 1;
-                }
+        if let Some((pos, end)) = self.selected_row_span() {
+            // If selection is above viewport, scroll up
+            if pos < self.scroll_offset {
+                self.scroll_offset = pos;
+            }
+            // If selection is below viewport, scroll down
+            else if end >= self.scroll_offset + viewport_height {
+                self.scroll_offset = (end + 1 - viewport_height).min(pos);
             }
         }
     }
 
-    /// Get the sort mode
-    pub fn get_sort_mode(&self) -> SortMode {
-        self.sort_mode
+    /// Get the order of the entries of each directory
+    pub fn sort(&self) -> TreeSort {
+        self.tree.sort()
+    }
+
+    /// Set the order of the entries of each directory
+    pub fn set_sort(&mut self, sort: TreeSort) {
+        self.tree.set_sort(sort);
     }
 
-    /// Set the sort mode
-    pub fn set_sort_mode(&mut self, mode: SortMode) {
-        self.sort_mode = mode;
-        // TODO: Re-sort children when sort mode changes
+    /// Whether size and modified time columns are shown
+    pub fn show_details(&self) -> bool {
+        self.show_details
+    }
+
+    /// Show or hide the size and modified time columns
+    pub fn set_show_details(&mut self, show: bool) {
+        self.show_details = show;
+    }
+
+    /// Number of rows above the entries: a header naming the columns and
+    /// the sort, shown in details mode or when not sorted by name
+    pub fn header_rows(&self) -> usize {
+        usize::from(self.show_details || self.sort() != TreeSort::default())
     }
 
     /// Get selected node entry (convenience method)
@@ -282,25 +384,74 @@ impl FileTreeView {
         }
     }
 
-    /// Get the index of the selected node in the visible list
+    /// Get the index of the selected node in the visible rows
     pub fn get_selected_index(&self) -> Option<usize> {
         if let Some(selected) = self.selected_node {
-            let visible = self.tree.get_visible_nodes();
-            visible.iter().position(|&id| id == selected)
+            let visible = self.tree.get_visible_rows();
+            visible
+                .iter()
+                .position(|row| row.node_id() == Some(selected))
         } else {
             None
         }
     }
 
-    /// Get visible node at index (accounting for scroll offset)
+    /// Get visible node at a row index (None for footer rows)
     pub fn get_node_at_index(&self, index: usize) -> Option<NodeId> {
-        let visible = self.tree.get_visible_nodes();
-        visible.get(index).copied()
+        let visible = self.tree.get_visible_rows();
+        visible.get(index).and_then(|row| row.node_id())
     }
 
-    /// Get the number of visible nodes
+    /// Get the number of visible rows
     pub fn visible_count(&self) -> usize {
-        self.tree.get_visible_nodes().len()
+        self.tree.get_visible_rows().len()
+    }
+
+    /// Cached number of entries in a directory, if it has been counted
+    pub fn entry_count(&self, path: &Path) -> Option<usize> {
+        self.entry_counts.get(path).map(|(count, _)| *count)
+    }
+
+    /// Cache the number of entries in a directory along with its mtime
+    pub fn set_entry_count(&mut self, path: PathBuf, count: usize, modified: Option<SystemTime>) {
+        self.entry_counts.insert(path, (count, modified));
+    }
+
+    /// Directories with cached entry counts and the mtime they were counted at
+    pub fn cached_entry_counts(&self) -> impl Iterator<Item = (&PathBuf, Option<SystemTime>)> {
+        self.entry_counts
+            .iter()
+            .map(|(path, (_, modified))| (path, *modified))
+    }
+
+    /// Forget a directory's cached entry count (its contents changed)
+    pub fn invalidate_entry_count(&mut self, path: &Path) {
+        self.entry_counts.remove(path);
+    }
+
+    /// Fill in metadata fetched for entries listed without it
+    ///
+    /// The metadata is also cached, for when the directory is expanded
+    /// again. Under a sort that uses metadata, the directories of the
+    /// entries are re-sorted.
+    pub fn set_entry_metadata(&mut self, entries: Vec<(PathBuf, FsMetadata)>) {
+        let mut dirs = HashSet::new();
+        for (path, metadata) in entries {
+            dirs.extend(self.tree.set_metadata(&path, metadata.clone()));
+            self.entry_metadata.insert(path, metadata);
+        }
+        if self.sort().uses_metadata() {
+            for dir in dirs {
+                self.tree.sort_children(dir);
+            }
+        }
+    }
+
+    /// Forget the cached metadata of an entry and of the entries directly
+    /// in it (they changed on disk)
+    pub fn invalidate_entry_metadata(&mut self, path: &Path) {
+        self.entry_metadata
+            .retain(|cached, _| cached != path && cached.parent() != Some(path));
     }
 
     /// Get reference to ignore patterns
@@ -403,15 +554,16 @@ mod tests {
 
         assert!(view.get_selected().is_some());
         assert_eq!(view.get_scroll_offset(), 0);
-        assert_eq!(view.get_sort_mode(), SortMode::Type);
+        assert_eq!(view.sort(), TreeSort::default());
+        assert_eq!(view.header_rows(), 0);
     }
 
     #[tokio::test]
-    async fn test_get_display_nodes() {
+    async fn test_get_display_rows() {
         let (_temp_dir, mut view) = create_test_view().await;
 
         // Initially only root
-        let display = view.get_display_nodes();
+        let display = view.get_display_rows();
         assert_eq!(display.len(), 1);
         assert_eq!(display[0].1, 0); // Root has depth 0
 
@@ -419,7 +571,7 @@ mod tests {
         let root_id = view.tree().root_id();
         view.tree_mut().expand_node(root_id).await.unwrap();
 
-        let display = view.get_display_nodes();
+        let display = view.get_display_rows();
         assert_eq!(display.len(), 4); // root + 3 children
 
         // Check depths
@@ -429,6 +581,27 @@ mod tests {
         assert_eq!(display[3].1, 1); // child
     }
 
+    #[tokio::test]
+    async fn test_sync_children_moves_removed_selection() {
+        let (temp_dir, mut view) = create_test_view().await;
+        let root_id = view.tree().root_id();
+        view.tree_mut().expand_node(root_id).await.unwrap();
+        let dir1 = temp_dir.path().join("dir1");
+        let dir1_id = view.tree().get_node_by_path(&dir1).unwrap().id;
+        view.tree_mut().expand_node(dir1_id).await.unwrap();
+        view.navigate_to_path(&dir1.join("file1.txt"));
+
+        std_fs::remove_file(dir1.join("file1.txt")).unwrap();
+        std_fs::write(dir1.join("file0.txt"), "content0").unwrap();
+        let manager = FsManager::new(Arc::new(LocalFsBackend::new()));
+        let entries = manager.list_dir_with_metadata(dir1.clone()).await.unwrap();
+        assert_eq!(view.sync_children(dir1_id, entries.clone()), 2);
+        assert_eq!(view.sync_children(dir1_id, entries), 0);
+
+        let selected = view.get_selected_entry().unwrap();
+        assert_eq!(selected.path, dir1.join("file2.txt"));
+    }
+
     #[tokio::test]
     async fn test_navigation() {
         let (_temp_dir, mut view) = create_test_view().await;
@@ -557,15 +730,77 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn test_sort_mode() {
+    async fn test_sort_and_details_header() {
         let (_temp_dir, mut view) = create_test_view().await;
 
-        assert_eq!(view.get_sort_mode(), SortMode::Type);
+        view.set_sort(TreeSort::default().next());
+        assert!(view.sort().descending);
+        assert_eq!(view.header_rows(), 1);
+
+        view.set_sort(TreeSort::default());
+        view.set_show_details(true);
+        assert_eq!(view.header_rows(), 1);
+    }
 
-        view.set_sort_mode(SortMode::Name);
-        assert_eq!(view.get_sort_mode(), SortMode::Name);
+    #[tokio::test]
+    async fn test_entry_metadata_cache() {
+        let (temp_dir, mut view) = create_test_view().await;
+        let root_id = view.tree().root_id();
+        let dir2 = temp_dir.path().join("dir2");
+        view.tree_mut().expand_node(root_id).await.unwrap();
+        let dir2_id = view.tree().get_node_by_path(&dir2).unwrap().id;
+
+        let listed = |name: &str| {
+            FsEntry::new(
+                dir2.join(name),
+                name.to_string(),
+                crate::services::fs::FsEntryType::File,
+            )
+        };
+        view.tree_mut().begin_expand(dir2_id).unwrap();
+        view.append_children(dir2_id, vec![listed("a.txt")]);
+        view.set_entry_metadata(vec![(dir2.join("a.txt"), FsMetadata::new().with_size(7))]);
+        let size = |view: &FileTreeView| {
+            view.tree()
+                .get_node_by_path(&dir2.join("a.txt"))
+                .and_then(|node| node.entry.metadata.as_ref())
+                .and_then(|metadata| metadata.size)
+        };
+        assert_eq!(size(&view), Some(7));
+
+        // Listed again, the entry gets the cached metadata until it changes
+        view.tree_mut().begin_expand(dir2_id).unwrap();
+        view.append_children(dir2_id, vec![listed("a.txt")]);
+        assert_eq!(size(&view), Some(7));
+        view.invalidate_entry_metadata(&dir2);
+        view.tree_mut().begin_expand(dir2_id).unwrap();
+        view.append_children(dir2_id, vec![listed("a.txt")]);
+        assert_eq!(size(&view), None);
+    }
+
+    #[tokio::test]
+    async fn test_append_keeps_selected_row_on_screen() {
+        let (temp_dir, mut view) = create_test_view().await;
+        let root_id = view.tree().root_id();
+        view.tree_mut().expand_node(root_id).await.unwrap();
+        view.select_last(); // file3.txt, row 3
+        view.set_scroll_offset(1);
+
+        // Entries sorting above the selection push it down a row each
+        let entries = ["a.txt", "b.txt"]
+            .iter()
+            .map(|name| {
+                FsEntry::new(
+                    temp_dir.path().join(name),
+                    name.to_string(),
+                    crate::services::fs::FsEntryType::File,
+                )
+            })
+            .collect();
+        view.append_children(root_id, entries);
 
-        view.set_sort_mode(SortMode::Modified);
-        assert_eq!(view.get_sort_mode(), SortMode::Modified);
+        assert_eq!(view.get_selected_entry().unwrap().name, "file3.txt");
+        assert_eq!(view.get_selected_index(), Some(5));
+        assert_eq!(view.get_scroll_offset(), 3);
     }
 }
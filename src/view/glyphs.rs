@@ -0,0 +1,381 @@
+//! Decorative glyphs used to draw the editor's chrome
+//!
+//! Every border, arrow, bullet and marker the view layer draws comes from the
+//! active [`Glyphs`] table, so a terminal that can't display Unicode
+//! box-drawing characters (a serial console, `LANG=C`, bitmap fonts) can get
+//! an all-ASCII UI by switching the table. `ui.ascii_only` selects the table;
+//! when unset it follows the locale detected at startup. Themes can override
+//! individual entries by name under a top-level `glyphs` object.
+//!
+//! The table is ambient state, like the ambiguous-width policy in
+//! [`crate::primitives::display_width`], so that render functions deep in the
+//! view layer don't need it threaded through. It is kept per thread: the
+//! editor is built and drawn on one thread, and editors in parallel tests
+//! don't see each other's table.
+
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, OnceLock};
+
+use ratatui::symbols::border;
+
+use crate::primitives::display_width::{char_width, str_width};
+
+macro_rules! glyph_table {
+    ($( $(#[$doc:meta])* $name:ident: $unicode:literal, $ascii:literal; )*) => {
+        /// The decorative glyphs the view layer draws with
+        #[derive(Debug, Clone, PartialEq, Eq)]
+        pub struct Glyphs {
+            /// Whether this is the ASCII-only table
+            pub ascii_only: bool,
+            $( $(#[$doc])* pub $name: &'static str, )*
+        }
+
+        impl Glyphs {
+            /// The default table, using Unicode box drawing and symbols
+            pub fn unicode() -> Self {
+                Self { ascii_only: false, $( $name: $unicode, )* }
+            }
+
+            /// The ASCII-only table for terminals without Unicode support
+            pub fn ascii() -> Self {
+                Self { ascii_only: true, $( $name: $ascii, )* }
+            }
+
+            /// Names of all entries, as used in theme `glyphs` overrides
+            pub const NAMES: &'static [&'static str] = &[$( stringify!($name), )*];
+
+            fn slot(&mut self, name: &str) -> Option<&mut &'static str> {
+                match name {
+                    $( stringify!($name) => Some(&mut self.$name), )*
+                    _ => None,
+                }
+            }
+        }
+    };
+}
+
+glyph_table! {
+    /// Horizontal border and separator line
+    border_horizontal: "─", "-";
+    /// Vertical border and separator line
+    border_vertical: "│", "|";
+    /// Top-left border corner
+    border_top_left: "┌", "+";
+    /// Top-right border corner
+    border_top_right: "┐", "+";
+    /// Bottom-left border corner
+    border_bottom_left: "└", "+";
+    /// Bottom-right border corner
+    border_bottom_right: "┘", "+";
+    /// Vertical guide drawn over the text area
+    guide: "│", "|";
+    /// Marker for an expanded tree node or map entry
+    expanded: "▼", "v";
+    /// Marker for a collapsed tree node or map entry
+    collapsed: ">", ">";
+    /// Marker for a directory that is still loading
+    loading: "⟳", "~";
+    /// Shown in place of truncated text
+    ellipsis: "…", "..";
+    /// List bullet and "has changes" marker in settings
+    bullet: "•", "*";
+    /// Unsaved-changes marker
+    modified: "●", "*";
+    /// Dot marking a line in the gutter (diagnostics, breakpoints)
+    gutter_dot: "●", "*";
+    /// Warning marker
+    warning: "⚠", "!";
+    /// Information marker
+    info: "ℹ", "i";
+    /// Error marker
+    error: "✗", "x";
+    /// Close button
+    close: "×", "x";
+    /// Maximize-split button
+    maximize: "□", "^";
+    /// Restore-split button
+    restore: "⧉", "v";
+    /// Marks a split whose input is locked
+    locked: "🔒", "#";
+    /// Marks a pinned buffer's tab
+    pinned: "📌", "^";
+    /// Add button in list and map controls
+    add_button: "[+]", "[+]";
+    /// Remove button in list and map controls
+    remove_button: "[x]", "[x]";
+    /// Checked checkbox in menus and dialogs
+    checkbox_on: "☑", "x";
+    /// Unchecked checkbox in menus and dialogs
+    checkbox_off: "☐", "-";
+    /// Up arrow, e.g. an open dropdown or ascending sort
+    arrow_up: "▲", "^";
+    /// Down arrow, e.g. a closed dropdown or descending sort
+    arrow_down: "▼", "v";
+    /// Up key in help text
+    key_up: "↑", "^";
+    /// Down key in help text
+    key_down: "↓", "v";
+    /// Left key in help text
+    key_left: "←", "<";
+    /// Right key in help text
+    key_right: "→", ">";
+    /// Arrow between a key and its action
+    maps_to: "→", "->";
+    /// Separator between a winbar's buffer name and key path
+    breadcrumb: "›", ">";
+    /// Tab marker for visible tabs
+    tab: "→", ">";
+    /// Visible space, where the theme's own symbol can't be drawn
+    space_mark: "·", ".";
+    /// Visible unusual space, where the theme's own symbol can't be drawn
+    unusual_space_mark: "⍽", "_";
+    /// Drawn at the cursor while waiting for the language server
+    waiting: "⋯", "~";
+    /// Bar marking the focused row
+    cursor_bar: "▌", ">";
+    /// Text cursor drawn in search fields
+    text_cursor: "█", "_";
+    /// Search field icon
+    search: "🔍", "/";
+    /// Frames of the spinner shown while progress is unknown
+    spinner: "⠋⠙⠹⠸⠼⠴⠦⠧⠇⠏", "|/-\\";
+    /// Filled part of a scrollbar
+    scrollbar_thumb: "█", "#";
+    /// Empty part of a scrollbar
+    scrollbar_track: "░", ":";
+    /// Drop zone on the left edge of a split
+    drop_left: "▌", "|";
+    /// Drop zone on the right edge of a split
+    drop_right: "▐", "|";
+    /// Drop zone on the top edge of a split
+    drop_top: "▀", "-";
+    /// Drop zone on the bottom edge of a split
+    drop_bottom: "▄", "-";
+}
+
+impl Glyphs {
+    /// The table for the given profile
+    pub fn for_profile(ascii_only: bool) -> Self {
+        if ascii_only {
+            Self::ascii()
+        } else {
+            Self::unicode()
+        }
+    }
+
+    /// Apply theme overrides by entry name.
+    ///
+    /// Unknown names are ignored. In the ASCII table, overrides containing
+    /// anything but printable ASCII are ignored too, so a theme can't
+    /// reintroduce multi-byte chrome.
+    pub fn with_overrides(mut self, overrides: &HashMap<String, String>) -> Self {
+        let ascii_only = self.ascii_only;
+        for (name, value) in overrides {
+            if ascii_only && !value.chars().all(|c| c.is_ascii() && !c.is_ascii_control()) {
+                continue;
+            }
+            match self.slot(name) {
+                Some(slot) => *slot = intern(value),
+                None => tracing::warn!("Unknown glyph '{}' in theme", name),
+            }
+        }
+        self
+    }
+
+    /// Border set for ratatui blocks
+    pub fn border_set(&self) -> border::Set {
+        border::Set {
+            top_left: self.border_top_left,
+            top_right: self.border_top_right,
+            bottom_left: self.border_bottom_left,
+            bottom_right: self.border_bottom_right,
+            vertical_left: self.border_vertical,
+            vertical_right: self.border_vertical,
+            horizontal_top: self.border_horizontal,
+            horizontal_bottom: self.border_horizontal,
+        }
+    }
+
+    /// Truncate `text` to at most `max_width` columns, ending in the ellipsis
+    /// if anything was cut.
+    pub fn truncate(&self, text: &str, max_width: usize) -> String {
+        if str_width(text) <= max_width {
+            return text.to_string();
+        }
+        let ellipsis_width = str_width(self.ellipsis);
+        if max_width < ellipsis_width {
+            return fit_width(text, max_width);
+        }
+        let mut out = fit_width(text, max_width - ellipsis_width);
+        out.push_str(self.ellipsis);
+        out
+    }
+
+    /// Swap the ellipsis inside translated text (e.g. "loading…") for this
+    /// table's
+    pub fn text<'a>(&self, text: &'a str) -> Cow<'a, str> {
+        if self.ellipsis != "…" && text.contains('…') {
+            Cow::Owned(text.replace('…', self.ellipsis))
+        } else {
+            Cow::Borrowed(text)
+        }
+    }
+}
+
+/// Keep the leading characters of `text` that fit in `width` columns,
+/// padding with spaces to exactly `width`.
+///
+/// Used for symbols supplied by plugins so that a wide or zero-width symbol
+/// can't shift the columns after it.
+pub fn fit_width(text: &str, width: usize) -> String {
+    let mut out = String::new();
+    let mut used = 0;
+    for c in text.chars() {
+        let w = char_width(c);
+        if used + w > width {
+            break;
+        }
+        out.push(c);
+        used += w;
+    }
+    out.push_str(&" ".repeat(width - used));
+    out
+}
+
+/// Theme overrides are rare and small; keep one copy of each so reloading a
+/// theme doesn't grow memory.
+fn intern(value: &str) -> &'static str {
+    static INTERNED: OnceLock<Mutex<HashSet<&'static str>>> = OnceLock::new();
+    let mut set = INTERNED
+        .get_or_init(Default::default)
+        .lock()
+        .unwrap_or_else(|e| e.into_inner());
+    if let Some(existing) = set.get(value) {
+        return existing;
+    }
+    let leaked: &'static str = Box::leak(value.to_string().into_boxed_str());
+    set.insert(leaked);
+    leaked
+}
+
+thread_local! {
+    static ACTIVE: RefCell<Rc<Glyphs>> = RefCell::new(Rc::new(Glyphs::unicode()));
+}
+
+/// The glyph table currently in use
+pub fn glyphs() -> Rc<Glyphs> {
+    ACTIVE.with(|active| active.borrow().clone())
+}
+
+/// Replace the glyph table used by the view layer
+pub fn set_glyphs(glyphs: Glyphs) {
+    ACTIVE.with(|active| *active.borrow_mut() = Rc::new(glyphs));
+}
+
+/// Select the table for `ui.ascii_only` (unset follows the locale) with a
+/// theme's overrides applied, and make it the active one
+pub fn configure(ascii_only: Option<bool>, overrides: &HashMap<String, String>) {
+    let ascii_only = ascii_only.unwrap_or_else(locale_ascii_only);
+    set_glyphs(Glyphs::for_profile(ascii_only).with_overrides(overrides));
+}
+
+/// Set from the locale at startup; the default when `ui.ascii_only` is unset.
+static LOCALE_ASCII_ONLY: AtomicBool = AtomicBool::new(false);
+
+/// Whether the locale detected at startup can't display Unicode
+pub fn locale_ascii_only() -> bool {
+    LOCALE_ASCII_ONLY.load(Ordering::Relaxed)
+}
+
+/// Detect from `LC_ALL`, `LC_CTYPE` and `LANG` (first one set wins) whether
+/// the terminal's locale lacks UTF-8, and remember it as the default for
+/// `ui.ascii_only`.
+pub fn detect_locale() {
+    LOCALE_ASCII_ONLY.store(
+        locale_lacks_utf8(|name| std::env::var(name).ok()),
+        Ordering::Relaxed,
+    );
+}
+
+fn locale_lacks_utf8(var: impl Fn(&str) -> Option<String>) -> bool {
+    let Some(locale) = ["LC_ALL", "LC_CTYPE", "LANG"]
+        .iter()
+        .filter_map(|name| var(name))
+        .find(|value| !value.is_empty())
+    else {
+        // No locale at all usually means a bare environment (cron, ssh with
+        // no forwarding) on a terminal that is UTF-8 anyway.
+        return false;
+    };
+    let locale = locale.to_ascii_lowercase();
+    !(locale.contains("utf-8") || locale.contains("utf8"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ascii_table_is_single_byte() {
+        let ascii = Glyphs::ascii();
+        for name in Glyphs::NAMES {
+            let value = *ascii.clone().slot(name).unwrap();
+            assert!(value.is_ascii(), "{} = {:?}", name, value);
+        }
+    }
+
+    #[test]
+    fn test_overrides() {
+        let overrides = HashMap::from([
+            ("expanded".to_string(), "-".to_string()),
+            ("modified".to_string(), "◆".to_string()),
+            ("no_such_glyph".to_string(), "?".to_string()),
+        ]);
+        let unicode = Glyphs::unicode().with_overrides(&overrides);
+        assert_eq!(unicode.expanded, "-");
+        assert_eq!(unicode.modified, "◆");
+
+        let ascii = Glyphs::ascii().with_overrides(&overrides);
+        assert_eq!(ascii.expanded, "-");
+        assert_eq!(ascii.modified, "*");
+    }
+
+    #[test]
+    fn test_truncate_and_fit() {
+        let ascii = Glyphs::ascii();
+        assert_eq!(ascii.truncate("short", 10), "short");
+        assert_eq!(ascii.truncate("a long name", 6), "a lo..");
+        assert_eq!(Glyphs::unicode().truncate("a long name", 6), "a lon…");
+        assert_eq!(fit_width("🔴", 1), " ");
+        assert_eq!(fit_width("ab", 1), "a");
+        assert_eq!(fit_width("", 2), "  ");
+    }
+
+    #[test]
+    fn test_locale_detection() {
+        let env = |pairs: &'static [(&'static str, &'static str)]| {
+            move |name: &str| {
+                pairs
+                    .iter()
+                    .find(|(k, _)| *k == name)
+                    .map(|(_, v)| v.to_string())
+            }
+        };
+        assert!(!locale_lacks_utf8(env(&[])));
+        assert!(!locale_lacks_utf8(env(&[("LANG", "en_US.UTF-8")])));
+        assert!(!locale_lacks_utf8(env(&[
+            ("LANG", "C"),
+            ("LC_ALL", "de_DE.utf8")
+        ])));
+        assert!(locale_lacks_utf8(env(&[("LANG", "C")])));
+        assert!(locale_lacks_utf8(env(&[
+            ("LC_ALL", "POSIX"),
+            ("LANG", "en_US.UTF-8")
+        ])));
+        assert!(locale_lacks_utf8(env(&[("LC_CTYPE", "en_US.ISO-8859-1")])));
+    }
+}
@@ -1,6 +1,10 @@
+use crate::model::edit_batch::EditBatch;
 use crate::model::marker::{MarkerId, MarkerList};
+use crate::primitives::display_width::str_width;
+use crate::view::glyphs::{fit_width, glyphs};
+use crate::view::theme::{DecorationColor, Theme};
 use ratatui::style::{Color, Style};
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, HashMap};
 
 /// Position of a margin in the editor
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -11,8 +15,162 @@ pub enum MarginPosition {
     Right,
 }
 
-/// A line indicator displayed in the gutter's indicator column
-/// Can be used for git status, breakpoints, bookmarks, etc.
+/// What a gutter column shows
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GutterColumnKind {
+    /// Diagnostics and the line indicators that name no column (git gutter,
+    /// bookmarks, ...)
+    Indicators,
+    /// Line numbers
+    LineNumbers,
+    /// A column registered by a plugin, showing the line indicators that name it
+    Plugin(String),
+}
+
+/// How the content of a gutter cell is aligned within its column
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GutterAlignment {
+    #[default]
+    Left,
+    Center,
+    Right,
+}
+
+impl GutterAlignment {
+    /// Parse "left", "center" or "right"
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "left" => Some(Self::Left),
+            "center" => Some(Self::Center),
+            "right" => Some(Self::Right),
+            _ => None,
+        }
+    }
+
+    /// `text` cut or padded to exactly `width` cells
+    pub fn pad(self, text: &str, width: usize) -> String {
+        let text = fit_width(text, width);
+        let text = text.trim_end_matches(' ');
+        let free = width.saturating_sub(str_width(text));
+        let before = match self {
+            Self::Left => 0,
+            Self::Center => free / 2,
+            Self::Right => free,
+        };
+        format!(
+            "{}{}{}",
+            " ".repeat(before),
+            text,
+            " ".repeat(free - before)
+        )
+    }
+}
+
+/// How wide a gutter column is
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GutterWidth {
+    /// Always this many cells
+    Fixed(usize),
+    /// Wide enough for the buffer's line numbers, and at least `min` cells
+    LineNumbers { min: usize },
+}
+
+/// A column of the left gutter
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GutterColumn {
+    pub kind: GutterColumnKind,
+    pub width: GutterWidth,
+    pub alignment: GutterAlignment,
+}
+
+impl GutterColumn {
+    /// Name of the built-in indicator column in `editor.gutter_columns`
+    pub const INDICATORS: &'static str = "indicators";
+    /// Name of the built-in line number column in `editor.gutter_columns`
+    pub const LINE_NUMBERS: &'static str = "line_numbers";
+
+    /// The one-cell indicator column
+    pub fn indicators() -> Self {
+        Self {
+            kind: GutterColumnKind::Indicators,
+            width: GutterWidth::Fixed(1),
+            alignment: GutterAlignment::Left,
+        }
+    }
+
+    /// The line number column, at least 4 digits wide
+    pub fn line_numbers() -> Self {
+        Self {
+            kind: GutterColumnKind::LineNumbers,
+            width: GutterWidth::LineNumbers { min: 4 },
+            alignment: GutterAlignment::Right,
+        }
+    }
+
+    /// A plugin's column
+    pub fn plugin(name: impl Into<String>, width: usize, alignment: GutterAlignment) -> Self {
+        Self {
+            kind: GutterColumnKind::Plugin(name.into()),
+            width: GutterWidth::Fixed(width),
+            alignment,
+        }
+    }
+
+    /// The name the column goes by in the configuration
+    pub fn name(&self) -> &str {
+        match &self.kind {
+            GutterColumnKind::Indicators => Self::INDICATORS,
+            GutterColumnKind::LineNumbers => Self::LINE_NUMBERS,
+            GutterColumnKind::Plugin(name) => name,
+        }
+    }
+
+    /// Lay out the gutter from the configured column `order` and `widths`
+    ///
+    /// Names in `order` that are neither built in nor among `plugin_columns`
+    /// are skipped. Plugin columns that `order` leaves out come first, in the
+    /// order given. A configured width replaces a column's own width (for
+    /// line numbers it is the minimum), and a width of 0 hides the column.
+    pub fn layout(
+        order: &[String],
+        widths: &HashMap<String, usize>,
+        plugin_columns: &[GutterColumn],
+    ) -> Vec<GutterColumn> {
+        let unlisted = plugin_columns
+            .iter()
+            .filter(|column| !order.iter().any(|name| name == column.name()))
+            .cloned();
+        let listed = order.iter().filter_map(|name| match name.as_str() {
+            Self::INDICATORS => Some(Self::indicators()),
+            Self::LINE_NUMBERS => Some(Self::line_numbers()),
+            _ => plugin_columns
+                .iter()
+                .find(|column| column.name() == name)
+                .cloned(),
+        });
+
+        let mut columns: Vec<GutterColumn> = Vec::new();
+        for mut column in unlisted.chain(listed) {
+            if columns.iter().any(|c| c.name() == column.name()) {
+                continue;
+            }
+            if let Some(&width) = widths.get(column.name()) {
+                if width == 0 {
+                    continue;
+                }
+                column.width = match column.width {
+                    GutterWidth::Fixed(_) => GutterWidth::Fixed(width),
+                    GutterWidth::LineNumbers { .. } => GutterWidth::LineNumbers { min: width },
+                };
+            }
+            columns.push(column);
+        }
+        columns
+    }
+}
+
+/// A line indicator displayed in a gutter column
+/// Can be used for git status, breakpoints, bookmarks, test coverage, etc.
 ///
 /// Indicators are anchored to byte positions via markers, so they automatically
 /// shift when text is inserted or deleted before them.
@@ -22,8 +180,12 @@ pub struct LineIndicator {
     pub symbol: String,
     /// The color of the indicator
     pub color: Color,
+    /// Theme color drawn instead of `color` when set
+    pub theme_color: Option<DecorationColor>,
     /// Priority for display when multiple indicators exist (higher wins)
     pub priority: i32,
+    /// Plugin column the indicator is shown in; `None` for the indicator column
+    pub column: Option<String>,
     /// Marker ID anchoring this indicator to a byte position
     /// The line number is derived from this position at render time
     pub marker_id: MarkerId,
@@ -35,11 +197,36 @@ impl LineIndicator {
         Self {
             symbol: symbol.into(),
             color,
+            theme_color: None,
             priority,
+            column: None,
             marker_id: MarkerId(0), // Placeholder, set by MarginManager
         }
     }
 
+    /// Show the indicator in the plugin column named `column`
+    pub fn in_column(mut self, column: impl Into<String>) -> Self {
+        self.column = Some(column.into());
+        self
+    }
+
+    /// Draw the indicator in a theme color, so it follows theme changes
+    ///
+    /// `color` is still drawn if the theme has no color by that name.
+    pub fn with_theme_color(mut self, theme_color: DecorationColor) -> Self {
+        self.theme_color = Some(theme_color);
+        self
+    }
+
+    /// A copy of this indicator with its theme color looked up in `theme`
+    pub fn resolved(&self, theme: &Theme) -> Self {
+        let mut indicator = self.clone();
+        if let Some(theme_color) = &self.theme_color {
+            indicator.color = theme_color.resolve(theme, self.color);
+        }
+        indicator
+    }
+
     /// Create a line indicator with a specific marker ID
     pub fn with_marker(
         symbol: impl Into<String>,
@@ -50,7 +237,9 @@ impl LineIndicator {
         Self {
             symbol: symbol.into(),
             color,
+            theme_color: None,
             priority,
+            column: None,
             marker_id,
         }
     }
@@ -127,10 +316,13 @@ pub struct MarginConfig {
     /// Position of the margin (left or right)
     pub position: MarginPosition,
 
-    /// Width of the margin in characters
+    /// Width of the line number column in characters
     /// For left margin with line numbers, this is calculated dynamically
     pub width: usize,
 
+    /// Gutter columns from left to right
+    pub columns: Vec<GutterColumn>,
+
     /// Whether this margin is enabled
     pub enabled: bool,
 
@@ -153,6 +345,7 @@ impl MarginConfig {
         Self {
             position: MarginPosition::Left,
             width: 4, // Minimum 4 digits for line numbers
+            columns: vec![GutterColumn::indicators(), GutterColumn::line_numbers()],
             enabled: true,
             show_separator: true,
             separator: " │ ".to_string(), // Separator with spaces: " │ " (space before for indicators, space after for readability)
@@ -166,6 +359,7 @@ impl MarginConfig {
         Self {
             position: MarginPosition::Right,
             width: 0,
+            columns: Vec::new(),
             enabled: false,
             show_separator: false,
             separator: String::new(),
@@ -174,12 +368,27 @@ impl MarginConfig {
         }
     }
 
-    /// Calculate the total width including indicator column and separator
-    /// Format: [indicator (1 char)][line_number (N chars)][separator (3 chars)]
+    /// The separator as drawn, with its line in the active glyph table
+    pub fn rendered_separator(&self) -> String {
+        self.separator.replace('│', glyphs().border_vertical)
+    }
+
+    /// Width of a column in characters
+    pub fn column_width(&self, column: &GutterColumn) -> usize {
+        match column.width {
+            GutterWidth::Fixed(width) => width,
+            GutterWidth::LineNumbers { .. } => self.width,
+        }
+    }
+
+    /// Calculate the total width including all columns and the separator
+    /// Default format: [indicator (1 char)][line_number (N chars)][separator (3 chars)]
     pub fn total_width(&self) -> usize {
         if self.enabled {
-            // 1 char for indicator column + line number width + separator
-            1 + self.width
+            self.columns
+                .iter()
+                .map(|column| self.column_width(column))
+                .sum::<usize>()
                 + if self.show_separator {
                     self.separator.chars().count()
                 } else {
@@ -189,6 +398,24 @@ impl MarginConfig {
             0
         }
     }
+
+    /// The column drawn at `x` cells from the left edge of the margin, if any
+    ///
+    /// The separator and anything past it belong to no column.
+    pub fn column_at(&self, x: usize) -> Option<&GutterColumn> {
+        if !self.enabled {
+            return None;
+        }
+        let mut start = 0;
+        for column in &self.columns {
+            let end = start + self.column_width(column);
+            if x < end {
+                return Some(column);
+            }
+            start = end;
+        }
+        None
+    }
 }
 
 /// A margin annotation for a specific line
@@ -247,7 +474,7 @@ impl MarginAnnotation {
         Self::new(
             line,
             MarginPosition::Left,
-            MarginContent::colored_symbol("●", Color::Red),
+            MarginContent::colored_symbol(glyphs().gutter_dot, Color::Red),
         )
     }
 
@@ -256,7 +483,7 @@ impl MarginAnnotation {
         Self::new(
             line,
             MarginPosition::Left,
-            MarginContent::colored_symbol("✗", Color::Red),
+            MarginContent::colored_symbol(glyphs().error, Color::Red),
         )
     }
 
@@ -265,7 +492,7 @@ impl MarginAnnotation {
         Self::new(
             line,
             MarginPosition::Left,
-            MarginContent::colored_symbol("⚠", Color::Yellow),
+            MarginContent::colored_symbol(glyphs().warning, Color::Yellow),
         )
     }
 
@@ -274,7 +501,7 @@ impl MarginAnnotation {
         Self::new(
             line,
             MarginPosition::Left,
-            MarginContent::colored_symbol("ℹ", Color::Blue),
+            MarginContent::colored_symbol(glyphs().info, Color::Blue),
         )
     }
 }
@@ -354,6 +581,11 @@ impl MarginManager {
         self.indicator_markers.adjust_for_delete(position, length);
     }
 
+    /// Adjust all indicator markers for edits made at many places at once
+    pub fn adjust_for_edits(&mut self, batch: &EditBatch) {
+        self.indicator_markers.adjust_for_edits(batch);
+    }
+
     /// Set a diagnostic indicator for a line
     pub fn set_diagnostic_indicator(&mut self, line: usize, symbol: String, color: Color) {
         self.diagnostic_indicators.insert(line, (symbol, color));
@@ -428,9 +660,43 @@ impl MarginManager {
         }
     }
 
+    /// Clear all line indicators, of every namespace, with their markers
+    pub fn clear_line_indicators(&mut self) {
+        for &marker_id in self.line_indicators.keys() {
+            self.indicator_markers.delete(MarkerId(marker_id));
+        }
+        self.line_indicators.clear();
+    }
+
+    /// Get the number of line indicators, counting each namespace's own
+    pub fn line_indicator_count(&self) -> usize {
+        self.line_indicators
+            .values()
+            .map(|by_namespace| by_namespace.len())
+            .sum()
+    }
+
+    /// Clear all line indicators shown in the plugin column `column`
+    pub fn clear_line_indicators_for_column(&mut self, column: &str) {
+        let mut markers_to_delete = Vec::new();
+
+        for (&marker_id, indicators) in self.line_indicators.iter_mut() {
+            indicators.retain(|_, indicator| indicator.column.as_deref() != Some(column));
+            if indicators.is_empty() {
+                markers_to_delete.push(marker_id);
+            }
+        }
+
+        for marker_id in markers_to_delete {
+            self.line_indicators.remove(&marker_id);
+            self.indicator_markers.delete(MarkerId(marker_id));
+        }
+    }
+
     /// Get the line indicator for a specific line number
     ///
-    /// This looks up all indicators whose markers resolve to the given line.
+    /// This looks up all indicators of the indicator column whose markers
+    /// resolve to the given line.
     /// Returns the highest priority indicator if multiple exist on the same line.
     ///
     /// Note: This is O(n) in the number of indicators. For rendering, prefer
@@ -448,7 +714,7 @@ impl MarginManager {
                 let indicator_line = get_line_fn(byte_pos);
                 if indicator_line == line {
                     // Found an indicator on this line, check if it's higher priority
-                    for indicator in indicators.values() {
+                    for indicator in indicators.values().filter(|i| i.column.is_none()) {
                         if best.is_none() || indicator.priority > best.unwrap().priority {
                             best = Some(indicator);
                         }
@@ -460,7 +726,7 @@ impl MarginManager {
         best
     }
 
-    /// Get indicators within a viewport byte range
+    /// Get indicators of the indicator column within a viewport byte range
     ///
     /// Only queries markers within `viewport_start..viewport_end`, avoiding
     /// iteration over the entire indicator set.
@@ -472,6 +738,44 @@ impl MarginManager {
         viewport_start: usize,
         viewport_end: usize,
         get_line_fn: impl Fn(usize) -> usize,
+    ) -> BTreeMap<usize, LineIndicator> {
+        self.column_indicators_for_viewport(viewport_start, viewport_end, &get_line_fn, None)
+    }
+
+    /// Get the indicators of each plugin column in the layout within a
+    /// viewport byte range, by column name and line number
+    pub fn get_plugin_column_indicators_for_viewport(
+        &self,
+        viewport_start: usize,
+        viewport_end: usize,
+        get_line_fn: impl Fn(usize) -> usize,
+    ) -> HashMap<String, BTreeMap<usize, LineIndicator>> {
+        self.left_config
+            .columns
+            .iter()
+            .filter_map(|column| match &column.kind {
+                GutterColumnKind::Plugin(name) => Some(name),
+                _ => None,
+            })
+            .map(|name| {
+                let by_line = self.column_indicators_for_viewport(
+                    viewport_start,
+                    viewport_end,
+                    &get_line_fn,
+                    Some(name),
+                );
+                (name.clone(), by_line)
+            })
+            .collect()
+    }
+
+    /// Highest priority indicator per line among those shown in `column`
+    fn column_indicators_for_viewport(
+        &self,
+        viewport_start: usize,
+        viewport_end: usize,
+        get_line_fn: &impl Fn(usize) -> usize,
+        column: Option<&str>,
     ) -> BTreeMap<usize, LineIndicator> {
         let mut by_line: BTreeMap<usize, LineIndicator> = BTreeMap::new();
 
@@ -485,7 +789,11 @@ impl MarginManager {
                 let line = get_line_fn(byte_pos);
 
                 // Get highest priority indicator for this marker
-                if let Some(indicator) = indicators.values().max_by_key(|ind| ind.priority) {
+                if let Some(indicator) = indicators
+                    .values()
+                    .filter(|ind| ind.column.as_deref() == column)
+                    .max_by_key(|ind| ind.priority)
+                {
                     // Check if this is higher priority than existing indicator on this line
                     if let Some(existing) = by_line.get(&line) {
                         if indicator.priority > existing.priority {
@@ -614,10 +922,24 @@ impl MarginManager {
             } else {
                 ((buffer_total_lines as f64).log10().floor() as usize) + 1
             };
-            self.left_config.width = digits.max(4);
+            let min = self
+                .left_config
+                .columns
+                .iter()
+                .find_map(|column| match column.width {
+                    GutterWidth::LineNumbers { min } => Some(min),
+                    GutterWidth::Fixed(_) => None,
+                })
+                .unwrap_or(4);
+            self.left_config.width = digits.max(min);
         }
     }
 
+    /// Replace the columns of the left margin
+    pub fn set_columns(&mut self, columns: Vec<GutterColumn>) {
+        self.left_config.columns = columns;
+    }
+
     /// Get the total width of the left margin (including separator)
     /// The separator includes the diagnostic indicator when present
     pub fn left_total_width(&self) -> usize {
@@ -692,6 +1014,92 @@ mod tests {
         assert_eq!(config.total_width(), 0);
     }
 
+    fn plugin_columns() -> Vec<GutterColumn> {
+        vec![
+            GutterColumn::plugin("coverage", 2, GutterAlignment::Left),
+            GutterColumn::plugin("breakpoints", 1, GutterAlignment::Center),
+        ]
+    }
+
+    fn names(columns: &[GutterColumn]) -> Vec<&str> {
+        columns.iter().map(|c| c.name()).collect()
+    }
+
+    #[test]
+    fn test_gutter_layout_order_and_visibility() {
+        let order: Vec<String> = ["line_numbers", "breakpoints", "blame", "indicators"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let columns = GutterColumn::layout(&order, &HashMap::new(), &plugin_columns());
+        // Unlisted plugin columns come first; unknown names are skipped
+        assert_eq!(
+            names(&columns),
+            ["coverage", "line_numbers", "breakpoints", "indicators"]
+        );
+
+        // Built-in columns left out are hidden
+        let order = vec!["line_numbers".to_string()];
+        let columns = GutterColumn::layout(&order, &HashMap::new(), &[]);
+        assert_eq!(names(&columns), ["line_numbers"]);
+    }
+
+    #[test]
+    fn test_gutter_layout_widths() {
+        let order = vec!["indicators".to_string(), "line_numbers".to_string()];
+        let widths = HashMap::from([
+            ("line_numbers".to_string(), 6),
+            ("coverage".to_string(), 3),
+            ("breakpoints".to_string(), 0),
+        ]);
+        let columns = GutterColumn::layout(&order, &widths, &plugin_columns());
+        assert_eq!(names(&columns), ["coverage", "indicators", "line_numbers"]);
+        assert_eq!(columns[0].width, GutterWidth::Fixed(3));
+        assert_eq!(columns[2].width, GutterWidth::LineNumbers { min: 6 });
+
+        let mut manager = MarginManager::new();
+        manager.set_columns(columns);
+        manager.update_width_for_buffer(100);
+        assert_eq!(manager.left_config.width, 6);
+    }
+
+    #[test]
+    fn test_three_column_width_and_hit_testing() {
+        let mut config = MarginConfig::left_default();
+        config.width = 4;
+        config.columns = vec![
+            GutterColumn::plugin("coverage", 2, GutterAlignment::Left),
+            GutterColumn::indicators(),
+            GutterColumn::line_numbers(),
+        ];
+        // 2 (coverage) + 1 (indicator) + 4 (line num) + 3 (separator)
+        assert_eq!(config.total_width(), 10);
+
+        let hit = |x| config.column_at(x).map(|c| c.name().to_string());
+        assert_eq!(hit(0).as_deref(), Some("coverage"));
+        assert_eq!(hit(1).as_deref(), Some("coverage"));
+        assert_eq!(hit(2).as_deref(), Some("indicators"));
+        assert_eq!(hit(3).as_deref(), Some("line_numbers"));
+        assert_eq!(hit(6).as_deref(), Some("line_numbers"));
+        assert_eq!(hit(7), None); // separator
+
+        config.enabled = false;
+        assert_eq!(config.column_at(0), None);
+    }
+
+    #[test]
+    fn test_gutter_alignment_pad() {
+        assert_eq!(GutterAlignment::Left.pad("●", 3), "●  ");
+        assert_eq!(GutterAlignment::Center.pad("●", 3), " ● ");
+        assert_eq!(GutterAlignment::Right.pad("●", 3), "  ●");
+        assert_eq!(GutterAlignment::Right.pad("abcd", 2), "ab");
+        assert_eq!(
+            GutterAlignment::from_name("center"),
+            Some(GutterAlignment::Center)
+        );
+        assert_eq!(GutterAlignment::from_name("middle"), None);
+    }
+
     #[test]
     fn test_margin_annotation_helpers() {
         let line_num = MarginAnnotation::line_number(5);
@@ -894,6 +1302,70 @@ mod tests {
         assert_eq!(breakpoint.unwrap().symbol, "●");
     }
 
+    #[test]
+    fn test_line_indicator_clear_all() {
+        let mut manager = MarginManager::new();
+        manager.set_line_indicator(
+            line_to_byte(1),
+            "git-gutter".to_string(),
+            LineIndicator::new("│", Color::Green, 10),
+        );
+        manager.set_line_indicator(
+            line_to_byte(2),
+            "breakpoints".to_string(),
+            LineIndicator::new("●", Color::Red, 20),
+        );
+        assert_eq!(manager.line_indicator_count(), 2);
+
+        manager.clear_line_indicators();
+        assert_eq!(manager.line_indicator_count(), 0);
+        assert!(manager.get_line_indicator(1, byte_to_line).is_none());
+        assert_eq!(manager.indicator_markers.marker_count(), 0);
+    }
+
+    #[test]
+    fn test_line_indicator_plugin_columns() {
+        let mut manager = MarginManager::new();
+        manager.set_columns(vec![
+            GutterColumn::plugin("coverage", 2, GutterAlignment::Left),
+            GutterColumn::indicators(),
+            GutterColumn::line_numbers(),
+        ]);
+
+        manager.set_line_indicator(
+            line_to_byte(2),
+            "git-gutter".to_string(),
+            LineIndicator::new("│", Color::Green, 10),
+        );
+        manager.set_line_indicator(
+            line_to_byte(2),
+            "coverage".to_string(),
+            LineIndicator::new("▌", Color::Red, 0).in_column("coverage"),
+        );
+
+        // A column's indicators don't compete with the indicator column's
+        let indicators = manager.get_indicators_for_viewport(0, 100, byte_to_line);
+        assert_eq!(indicators.get(&2).unwrap().symbol, "│");
+        assert_eq!(
+            manager.get_line_indicator(2, byte_to_line).unwrap().symbol,
+            "│"
+        );
+
+        let columns = manager.get_plugin_column_indicators_for_viewport(0, 100, byte_to_line);
+        assert_eq!(columns.len(), 1);
+        assert_eq!(columns["coverage"].get(&2).unwrap().symbol, "▌");
+
+        // Columns no longer in the layout aren't queried
+        manager.set_columns(vec![GutterColumn::indicators()]);
+        assert!(manager
+            .get_plugin_column_indicators_for_viewport(0, 100, byte_to_line)
+            .is_empty());
+
+        manager.clear_line_indicators_for_column("coverage");
+        manager.clear_line_indicators_for_namespace("git-gutter");
+        assert!(manager.line_indicators.is_empty());
+    }
+
     #[test]
     fn test_line_indicator_remove_specific() {
         let mut manager = MarginManager::new();
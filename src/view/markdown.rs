@@ -4,6 +4,7 @@
 //! hover documentation, and other UI elements. It also provides word
 //! wrapping utilities for styled text.
 
+use crate::view::glyphs::glyphs;
 use pulldown_cmark::{Event, Options, Parser, Tag, TagEnd};
 use ratatui::style::{Color, Modifier, Style};
 
@@ -383,7 +384,10 @@ pub fn parse_markdown(text: &str, theme: &crate::view::theme::Theme) -> Vec<Styl
                 // Horizontal rule
                 lines.push(StyledLine::new());
                 if let Some(line) = lines.last_mut() {
-                    line.push("─".repeat(40), Style::default().fg(Color::DarkGray));
+                    line.push(
+                        glyphs().border_horizontal.repeat(40),
+                        Style::default().fg(Color::DarkGray),
+                    );
                 }
                 lines.push(StyledLine::new());
             }
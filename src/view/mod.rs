@@ -2,16 +2,20 @@
 //!
 //! This module contains all presentation and rendering components.
 
+pub mod ambiguous_width;
 pub mod color_support;
 pub mod controls;
 pub mod dimming;
 pub mod file_browser_input;
 pub mod file_tree;
+pub mod glyphs;
 pub mod margin;
 pub mod markdown;
+pub mod notifications;
 pub mod overlay;
 pub mod popup;
 pub mod popup_input;
+pub mod progress;
 pub mod prompt;
 pub mod prompt_input;
 pub mod query_replace_input;
@@ -20,6 +24,7 @@ pub mod semantic_highlight_cache;
 pub mod settings;
 pub mod split;
 pub mod stream;
+pub mod terminal_effects;
 pub mod theme;
 pub mod ui;
 pub mod viewport;
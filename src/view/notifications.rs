@@ -0,0 +1,583 @@
+//! Toast notifications stacked above the status bar
+//!
+//! Unlike the status message, notifications queue: each one stays for its
+//! own timeout, at most [`MAX_VISIBLE`] are shown at once (the rest wait,
+//! counted in a "+N more" line), and a timeout only starts once its
+//! notification is on screen. The most recent notifications are also kept in
+//! a bounded history for "Show Notification History".
+
+use crate::primitives::display_width::str_width;
+use crate::view::glyphs::glyphs;
+use crate::view::markdown::wrap_text_line;
+use crate::view::theme::Theme;
+use ratatui::layout::Rect;
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Clear, Paragraph};
+use ratatui::Frame;
+use rust_i18n::t;
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+/// Notifications on screen at once
+pub const MAX_VISIBLE: usize = 3;
+
+/// Notifications kept for the history
+pub const HISTORY_CAPACITY: usize = 50;
+
+/// Timeout of notifications that don't set one
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Widest a notification gets, border included
+const MAX_WIDTH: u16 = 50;
+
+/// Narrower screens get no notifications (they stay in the history)
+const MIN_WIDTH: u16 = 12;
+
+/// Message lines shown before truncating
+const MAX_MESSAGE_LINES: usize = 3;
+
+/// Id for a notification that wasn't given one
+pub fn next_notification_id() -> String {
+    static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+    format!("notification-{}", NEXT_ID.fetch_add(1, Ordering::Relaxed))
+}
+
+/// How important a notification is, which picks its color
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NotificationSeverity {
+    #[default]
+    Info,
+    Warning,
+    Error,
+}
+
+impl NotificationSeverity {
+    /// Parse a plugin-supplied severity, defaulting to info
+    pub fn parse(name: &str) -> Self {
+        match name {
+            "warning" => Self::Warning,
+            "error" => Self::Error,
+            _ => Self::Info,
+        }
+    }
+
+    /// Translated name, used as the notification title
+    pub fn label(self) -> String {
+        match self {
+            Self::Info => t!("notification.info").to_string(),
+            Self::Warning => t!("notification.warning").to_string(),
+            Self::Error => t!("notification.error").to_string(),
+        }
+    }
+
+    fn color(self, theme: &Theme) -> Color {
+        match self {
+            Self::Info => theme.diagnostic_info_fg,
+            Self::Warning => theme.diagnostic_warning_fg,
+            Self::Error => theme.diagnostic_error_fg,
+        }
+    }
+}
+
+/// Button of a notification
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NotificationAction {
+    pub id: String,
+    pub label: String,
+}
+
+/// A notification, queued or on screen
+#[derive(Debug, Clone)]
+pub struct Notification {
+    pub id: String,
+    pub message: String,
+    pub severity: NotificationSeverity,
+    pub actions: Vec<NotificationAction>,
+    /// None keeps the notification until it is dismissed
+    pub timeout: Option<Duration>,
+    /// When it appeared on screen
+    shown_at: Option<Instant>,
+}
+
+impl Notification {
+    pub fn new(id: String, severity: NotificationSeverity, message: String) -> Self {
+        Self {
+            id,
+            message,
+            severity,
+            actions: Vec::new(),
+            timeout: Some(DEFAULT_TIMEOUT),
+            shown_at: None,
+        }
+    }
+
+    pub fn with_actions(mut self, actions: Vec<NotificationAction>) -> Self {
+        self.actions = actions;
+        self
+    }
+
+    pub fn with_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    fn expired(&self, now: Instant) -> bool {
+        match (self.shown_at, self.timeout) {
+            (Some(shown_at), Some(timeout)) => now.saturating_duration_since(shown_at) >= timeout,
+            _ => false,
+        }
+    }
+}
+
+/// Notification (and button) that has keyboard focus
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Focus {
+    notification: usize,
+    /// None when the notification has no buttons
+    action: Option<usize>,
+}
+
+/// Queued and shown notifications, and the history
+#[derive(Debug, Default)]
+pub struct NotificationStack {
+    /// Oldest first; the first [`MAX_VISIBLE`] are on screen
+    queue: VecDeque<Notification>,
+    /// Oldest first, at most [`HISTORY_CAPACITY`]
+    history: VecDeque<Notification>,
+    focus: Option<Focus>,
+}
+
+impl NotificationStack {
+    /// Queue a notification, replacing a queued one with the same id
+    pub fn push(&mut self, notification: Notification, now: Instant) {
+        self.remember(notification.clone());
+        if let Some(existing) = self.queue.iter_mut().find(|n| n.id == notification.id) {
+            let shown_at = existing.shown_at.map(|_| now);
+            *existing = Notification {
+                shown_at,
+                ..notification
+            };
+            self.clamp_focus();
+        } else {
+            self.queue.push_back(notification);
+        }
+        self.show_queued(now);
+    }
+
+    /// Change the message of a queued notification in place
+    ///
+    /// Unlike pushing a replacement, this doesn't restart its timeout or add
+    /// it to the history again, so it suits notifications that change often.
+    /// Returns false if there is no such notification.
+    pub fn update_message(&mut self, id: &str, message: String) -> bool {
+        match self.queue.iter_mut().find(|n| n.id == id) {
+            Some(notification) => {
+                notification.message = message;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Remove the notifications whose timeout passed, oldest first
+    ///
+    /// Timeouts are paused while the notifications have focus.
+    pub fn expire(&mut self, now: Instant) -> Vec<Notification> {
+        if self.focus.is_some() {
+            return Vec::new();
+        }
+        let mut expired = Vec::new();
+        let mut i = 0;
+        while i < self.queue.len() {
+            if self.queue[i].expired(now) {
+                expired.extend(self.queue.remove(i));
+            } else {
+                i += 1;
+            }
+        }
+        self.show_queued(now);
+        expired
+    }
+
+    /// Remove a notification
+    pub fn dismiss(&mut self, id: &str, now: Instant) -> Option<Notification> {
+        let index = self.queue.iter().position(|n| n.id == id)?;
+        let notification = self.queue.remove(index)?;
+        self.clamp_focus();
+        self.show_queued(now);
+        Some(notification)
+    }
+
+    /// Notifications on screen, oldest first
+    pub fn visible(&self) -> impl Iterator<Item = &Notification> {
+        self.queue.iter().take(MAX_VISIBLE)
+    }
+
+    /// Notifications waiting for room on screen
+    pub fn hidden_count(&self) -> usize {
+        self.queue.len().saturating_sub(MAX_VISIBLE)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.queue.is_empty()
+    }
+
+    /// Recent notifications, oldest first
+    pub fn history(&self) -> impl DoubleEndedIterator<Item = &Notification> {
+        self.history.iter()
+    }
+
+    /// Give the newest notification on screen keyboard focus
+    pub fn focus(&mut self) -> bool {
+        let visible = self.queue.len().min(MAX_VISIBLE);
+        if visible == 0 {
+            return false;
+        }
+        let notification = visible - 1;
+        self.focus = Some(Focus {
+            notification,
+            action: (!self.queue[notification].actions.is_empty()).then_some(0),
+        });
+        true
+    }
+
+    pub fn unfocus(&mut self) {
+        self.focus = None;
+    }
+
+    pub fn is_focused(&self) -> bool {
+        self.focus.is_some()
+    }
+
+    /// Move focus through the buttons of the notifications on screen
+    ///
+    /// A notification without buttons counts as one stop.
+    pub fn move_focus(&mut self, forward: bool) {
+        let Some(focus) = self.focus else {
+            return;
+        };
+        let stops: Vec<Focus> = self
+            .visible()
+            .enumerate()
+            .flat_map(|(notification, n)| {
+                let actions: Vec<Option<usize>> = if n.actions.is_empty() {
+                    vec![None]
+                } else {
+                    (0..n.actions.len()).map(Some).collect()
+                };
+                actions.into_iter().map(move |action| Focus {
+                    notification,
+                    action,
+                })
+            })
+            .collect();
+        let Some(current) = stops.iter().position(|stop| *stop == focus) else {
+            return;
+        };
+        let next = if forward {
+            (current + 1) % stops.len()
+        } else {
+            (current + stops.len() - 1) % stops.len()
+        };
+        self.focus = Some(stops[next]);
+    }
+
+    /// Id of the focused notification and of its focused button, if any
+    pub fn focused(&self) -> Option<(&str, Option<&str>)> {
+        let focus = self.focus?;
+        let notification = self.queue.get(focus.notification)?;
+        let action = focus
+            .action
+            .and_then(|i| notification.actions.get(i))
+            .map(|a| a.id.as_str());
+        Some((notification.id.as_str(), action))
+    }
+
+    fn remember(&mut self, notification: Notification) {
+        if self.history.len() == HISTORY_CAPACITY {
+            self.history.pop_front();
+        }
+        self.history.push_back(notification);
+    }
+
+    /// Start the timeouts of notifications that just got on screen
+    fn show_queued(&mut self, now: Instant) {
+        for notification in self.queue.iter_mut().take(MAX_VISIBLE) {
+            notification.shown_at.get_or_insert(now);
+        }
+    }
+
+    /// Keep focus on a notification that still exists, or drop it
+    fn clamp_focus(&mut self) {
+        let visible = self.queue.len().min(MAX_VISIBLE);
+        if visible == 0 {
+            self.focus = None;
+        } else if self.focus.is_some() {
+            self.focus();
+        }
+    }
+}
+
+/// Where the notifications were drawn, for mouse clicks
+#[derive(Debug, Clone, Default)]
+pub struct NotificationLayout {
+    /// (notification id, area)
+    pub areas: Vec<(String, Rect)>,
+    /// (notification id, action id, area)
+    pub buttons: Vec<(String, String, Rect)>,
+}
+
+/// Cut `text` to `width` columns, ending in the ellipsis if anything was cut
+fn truncate(text: &str, width: usize) -> String {
+    glyphs().truncate(text, width)
+}
+
+/// Message lines of a notification `width` columns wide
+fn message_lines(message: &str, width: usize) -> Vec<String> {
+    let mut lines: Vec<String> = message
+        .lines()
+        .flat_map(|line| wrap_text_line(line, width))
+        .collect();
+    if lines.len() > MAX_MESSAGE_LINES {
+        lines.truncate(MAX_MESSAGE_LINES);
+        let last = lines.pop().unwrap_or_default();
+        lines.push(truncate(
+            &format!("{}{}", last.trim_end(), glyphs().ellipsis),
+            width,
+        ));
+    }
+    lines
+}
+
+/// Draw the notifications on screen in the bottom-right corner of `area`
+///
+/// The newest is at the bottom; notifications that don't fit, like the ones
+/// still queued, are counted in a "+N more" line above the stack.
+pub fn render_notifications(
+    frame: &mut Frame,
+    area: Rect,
+    stack: &NotificationStack,
+    theme: &Theme,
+) -> NotificationLayout {
+    let mut layout = NotificationLayout::default();
+    if stack.is_empty() || area.width < MIN_WIDTH || area.height < 3 {
+        return layout;
+    }
+    let width = area.width.min(MAX_WIDTH);
+    let inner_width = width.saturating_sub(4) as usize;
+    let x = area.x + area.width - width;
+    let focused = stack.focused();
+
+    let visible: Vec<&Notification> = stack.visible().collect();
+    let mut bottom = area.y + area.height;
+    let mut drawn = 0;
+    for notification in visible.iter().rev() {
+        let lines = message_lines(&notification.message, inner_width);
+        let buttons = !notification.actions.is_empty();
+        let height = lines.len() as u16 + 2 + u16::from(buttons);
+        // Leave a row for the "+N more" line if needed
+        let hidden = visible.len() - drawn - 1 + stack.hidden_count();
+        let reserved = u16::from(hidden > 0);
+        if bottom < area.y + height + reserved {
+            break;
+        }
+        let rect = Rect::new(x, bottom - height, width, height);
+        bottom -= height;
+        drawn += 1;
+
+        let color = notification.severity.color(theme);
+        let is_focused = focused.is_some_and(|(id, _)| id == notification.id);
+        let mut border = Style::default().fg(color);
+        if is_focused {
+            border = border.add_modifier(Modifier::BOLD);
+        }
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_set(glyphs().border_set())
+            .border_style(border)
+            .title(Span::styled(
+                format!(" {} ", notification.severity.label()),
+                Style::default().fg(color).add_modifier(Modifier::BOLD),
+            ))
+            .style(Style::default().fg(theme.popup_text_fg).bg(theme.popup_bg));
+        let mut text: Vec<Line> = lines
+            .into_iter()
+            .map(|line| Line::from(format!(" {}", line)))
+            .collect();
+
+        if buttons {
+            let mut spans = vec![Span::raw(" ")];
+            let mut col = rect.x + 2;
+            let end = rect.x + rect.width - 1;
+            for action in &notification.actions {
+                let room = end.saturating_sub(col) as usize;
+                if room < 3 {
+                    break;
+                }
+                let label = truncate(&format!("[{}]", action.label), room);
+                let label_width = str_width(&label) as u16;
+                let selected = focused == Some((notification.id.as_str(), Some(&action.id)));
+                let style = if selected {
+                    Style::default()
+                        .fg(theme.popup_text_fg)
+                        .bg(theme.popup_selection_bg)
+                        .add_modifier(Modifier::BOLD)
+                } else {
+                    Style::default().fg(color)
+                };
+                layout.buttons.push((
+                    notification.id.clone(),
+                    action.id.clone(),
+                    Rect::new(col, rect.y + rect.height - 2, label_width, 1),
+                ));
+                spans.push(Span::styled(label, style));
+                spans.push(Span::raw(" "));
+                col += label_width + 1;
+            }
+            text.push(Line::from(spans));
+        }
+
+        frame.render_widget(Clear, rect);
+        frame.render_widget(Paragraph::new(text).block(block), rect);
+        layout.areas.push((notification.id.clone(), rect));
+    }
+
+    let hidden = visible.len() - drawn + stack.hidden_count();
+    if hidden > 0 && bottom > area.y {
+        let more = truncate(
+            &format!(" {} ", t!("notification.more", count = hidden)),
+            width as usize,
+        );
+        let more_width = str_width(&more) as u16;
+        let rect = Rect::new(area.x + area.width - more_width, bottom - 1, more_width, 1);
+        frame.render_widget(Clear, rect);
+        frame.render_widget(
+            Paragraph::new(more).style(Style::default().fg(theme.popup_text_fg).bg(theme.popup_bg)),
+            rect,
+        );
+    }
+    layout
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::time_source::{TestTimeSource, TimeSource};
+
+    fn info(id: &str) -> Notification {
+        Notification::new(id.to_string(), NotificationSeverity::Info, id.to_string())
+    }
+
+    fn ids<'a>(notifications: impl Iterator<Item = &'a Notification>) -> Vec<&'a str> {
+        notifications.map(|n| n.id.as_str()).collect()
+    }
+
+    #[test]
+    fn test_queued_notifications_wait_for_room() {
+        let clock = TestTimeSource::new();
+        let mut stack = NotificationStack::default();
+        for id in ["a", "b", "c", "d", "e"] {
+            stack.push(info(id), clock.now());
+        }
+        assert_eq!(ids(stack.visible()), vec!["a", "b", "c"]);
+        assert_eq!(stack.hidden_count(), 2);
+
+        // "d" arrives on screen when "a" times out and gets a full timeout
+        clock.advance(DEFAULT_TIMEOUT);
+        let expired = stack.expire(clock.now());
+        assert_eq!(ids(expired.iter()), vec!["a", "b", "c"]);
+        assert_eq!(ids(stack.visible()), vec!["d", "e"]);
+        clock.advance(DEFAULT_TIMEOUT / 2);
+        assert!(stack.expire(clock.now()).is_empty());
+        clock.advance(DEFAULT_TIMEOUT / 2);
+        assert_eq!(stack.expire(clock.now()).len(), 2);
+        assert!(stack.is_empty());
+        assert_eq!(ids(stack.history()), vec!["a", "b", "c", "d", "e"]);
+    }
+
+    #[test]
+    fn test_sticky_and_focused_notifications_stay() {
+        let clock = TestTimeSource::new();
+        let mut stack = NotificationStack::default();
+        stack.push(info("sticky").with_timeout(None), clock.now());
+        stack.push(info("short"), clock.now());
+
+        assert!(stack.focus());
+        clock.advance(DEFAULT_TIMEOUT * 2);
+        assert!(stack.expire(clock.now()).is_empty());
+
+        stack.unfocus();
+        assert_eq!(ids(stack.expire(clock.now()).iter()), vec!["short"]);
+        assert_eq!(ids(stack.visible()), vec!["sticky"]);
+        assert!(stack.dismiss("sticky", clock.now()).is_some());
+        assert!(stack.is_empty());
+    }
+
+    #[test]
+    fn test_focus_moves_through_buttons() {
+        let clock = TestTimeSource::new();
+        let mut stack = NotificationStack::default();
+        stack.push(info("plain"), clock.now());
+        let action = |id: &str| NotificationAction {
+            id: id.to_string(),
+            label: id.to_string(),
+        };
+        stack.push(
+            info("ask").with_actions(vec![action("yes"), action("no")]),
+            clock.now(),
+        );
+
+        assert!(stack.focus());
+        assert_eq!(stack.focused(), Some(("ask", Some("yes"))));
+        stack.move_focus(true);
+        assert_eq!(stack.focused(), Some(("ask", Some("no"))));
+        stack.move_focus(true);
+        assert_eq!(stack.focused(), Some(("plain", None)));
+        stack.move_focus(false);
+        assert_eq!(stack.focused(), Some(("ask", Some("no"))));
+
+        stack.dismiss("ask", clock.now());
+        assert_eq!(stack.focused(), Some(("plain", None)));
+        stack.dismiss("plain", clock.now());
+        assert!(!stack.is_focused());
+    }
+
+    #[test]
+    fn test_update_message_keeps_history() {
+        let clock = TestTimeSource::new();
+        let mut stack = NotificationStack::default();
+        stack.push(info("progress"), clock.now());
+        clock.advance(DEFAULT_TIMEOUT / 2);
+        assert!(stack.update_message("progress", "50%".to_string()));
+        assert!(!stack.update_message("missing", "50%".to_string()));
+
+        assert_eq!(stack.visible().next().unwrap().message, "50%");
+        assert_eq!(stack.history().count(), 1);
+        clock.advance(DEFAULT_TIMEOUT / 2);
+        assert_eq!(ids(stack.expire(clock.now()).iter()), vec!["progress"]);
+    }
+
+    #[test]
+    fn test_history_is_bounded() {
+        let clock = TestTimeSource::new();
+        let mut stack = NotificationStack::default();
+        for i in 0..HISTORY_CAPACITY + 5 {
+            let id = i.to_string();
+            stack.push(info(&id), clock.now());
+            stack.dismiss(&id, clock.now());
+        }
+        assert_eq!(stack.history().count(), HISTORY_CAPACITY);
+        assert_eq!(stack.history().next().map(|n| n.id.as_str()), Some("5"));
+    }
+
+    #[test]
+    fn test_long_messages_are_truncated() {
+        let lines = message_lines(&"word ".repeat(40), 20);
+        assert_eq!(lines.len(), MAX_MESSAGE_LINES);
+        assert!(lines[2].ends_with('…'));
+        assert!(lines.iter().all(|line| str_width(line) <= 20));
+        assert_eq!(truncate("[Restart]", 6), "[Rest…");
+    }
+}
@@ -1,32 +1,67 @@
 use crate::model::marker::{MarkerId, MarkerList};
-use ratatui::style::{Color, Style};
+use crate::primitives::interner::Symbol;
+use crate::view::theme::{DecorationColor, Theme, ThemedStyle};
+use ratatui::style::{Color, Modifier, Style};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
 use std::ops::Range;
 use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::OnceLock;
 
 /// Opaque handle for an overlay, returned to plugins for later removal.
-/// Internally a String (can be UUID, composite key, etc.) but plugins treat it as opaque.
+/// Plugins see it as a string ("ovl_N"); internally it is the number N, which
+/// also orders overlays by when they were created. Any other string a plugin
+/// passes is interned.
 /// This is stable across text edits (unlike line-number-based IDs).
-#[derive(Debug, Clone, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
-pub struct OverlayHandle(pub String);
+#[derive(
+    Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, serde::Serialize, serde::Deserialize,
+)]
+#[serde(from = "String", into = "String")]
+pub struct OverlayHandle(u64);
+
+/// Set in handles made from other strings, whose number is the symbol's
+const NAMED_HANDLE: u64 = 1 << 63;
 
 impl OverlayHandle {
     /// Generate a new unique handle
     pub fn new() -> Self {
         static NEXT_HANDLE: AtomicU64 = AtomicU64::new(1);
-        Self(format!(
-            "ovl_{}",
-            NEXT_HANDLE.fetch_add(1, Ordering::Relaxed)
-        ))
+        Self(NEXT_HANDLE.fetch_add(1, Ordering::Relaxed))
     }
 
     /// Create a handle from a string (for internal use)
     pub fn from_string(s: String) -> Self {
-        Self(s)
+        let number = s
+            .strip_prefix("ovl_")
+            .and_then(|n| n.parse::<u64>().ok())
+            .filter(|n| *n < NAMED_HANDLE && format!("ovl_{}", n) == s);
+        match number {
+            Some(number) => Self(number),
+            None => Self(NAMED_HANDLE | u64::from(Symbol::intern(&s).index())),
+        }
     }
+}
 
-    /// Get the internal string representation
-    pub fn as_str(&self) -> &str {
-        &self.0
+impl fmt::Display for OverlayHandle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.0 & NAMED_HANDLE != 0 {
+            if let Some(symbol) = Symbol::from_index((self.0 & !NAMED_HANDLE) as u32) {
+                return f.write_str(symbol.as_str());
+            }
+        }
+        write!(f, "ovl_{}", self.0)
+    }
+}
+
+impl From<String> for OverlayHandle {
+    fn from(s: String) -> Self {
+        Self::from_string(s)
+    }
+}
+
+impl From<OverlayHandle> for String {
+    fn from(handle: OverlayHandle) -> Self {
+        handle.to_string()
     }
 }
 
@@ -38,27 +73,41 @@ impl Default for OverlayHandle {
 
 /// Namespace for grouping overlays (for efficient bulk removal).
 /// Plugins create a namespace once and use it for all their overlays.
+/// The name is interned, so overlays share it.
 #[derive(Debug, Clone, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
-pub struct OverlayNamespace(pub String);
+#[serde(from = "String", into = "String")]
+pub struct OverlayNamespace(Symbol);
 
 impl OverlayNamespace {
     /// Generate a new unique namespace
     pub fn new() -> Self {
         static NEXT_NAMESPACE: AtomicU64 = AtomicU64::new(1);
-        Self(format!(
+        Self(Symbol::intern(&format!(
             "ns_{}",
             NEXT_NAMESPACE.fetch_add(1, Ordering::Relaxed)
-        ))
+        )))
     }
 
     /// Create a namespace from a string (for plugin registration)
     pub fn from_string(s: String) -> Self {
-        Self(s)
+        Self(Symbol::intern(&s))
     }
 
     /// Get the internal string representation
-    pub fn as_str(&self) -> &str {
-        &self.0
+    pub fn as_str(&self) -> &'static str {
+        self.0.as_str()
+    }
+}
+
+impl From<String> for OverlayNamespace {
+    fn from(s: String) -> Self {
+        Self::from_string(s)
+    }
+}
+
+impl From<OverlayNamespace> for String {
+    fn from(namespace: OverlayNamespace) -> Self {
+        namespace.as_str().to_string()
     }
 }
 
@@ -79,6 +128,8 @@ pub enum OverlayFace {
     Foreground { color: Color },
     /// Combined style with multiple attributes
     Style { style: Style },
+    /// Combined style with theme colors, resolved when drawing
+    Themed { style: ThemedStyle },
 }
 
 /// Style of underline
@@ -94,6 +145,199 @@ pub enum UnderlineStyle {
     Dashed,
 }
 
+/// A color in 32 bits: a tag in the top byte, the color in the rest
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct PackedColor(u32);
+
+const COLOR_RGB: u32 = 1;
+const COLOR_INDEXED: u32 = 2;
+/// A color of [`NAMED_COLORS`], by index
+const COLOR_NAMED: u32 = 3;
+/// A theme color name, by symbol
+const COLOR_THEME: u32 = 4;
+
+const NAMED_COLORS: [Color; 17] = [
+    Color::Reset,
+    Color::Black,
+    Color::Red,
+    Color::Green,
+    Color::Yellow,
+    Color::Blue,
+    Color::Magenta,
+    Color::Cyan,
+    Color::Gray,
+    Color::DarkGray,
+    Color::LightRed,
+    Color::LightGreen,
+    Color::LightYellow,
+    Color::LightBlue,
+    Color::LightMagenta,
+    Color::LightCyan,
+    Color::White,
+];
+
+impl PackedColor {
+    const NONE: Self = Self(0);
+
+    fn tagged(tag: u32, value: u32) -> Self {
+        Self((tag << 24) | (value & 0xFF_FFFF))
+    }
+
+    fn pack(color: Color) -> Self {
+        match color {
+            Color::Rgb(r, g, b) => Self::tagged(COLOR_RGB, u32::from_be_bytes([0, r, g, b])),
+            Color::Indexed(index) => Self::tagged(COLOR_INDEXED, index.into()),
+            named => {
+                let index = NAMED_COLORS.iter().position(|c| *c == named).unwrap_or(0);
+                Self::tagged(COLOR_NAMED, index as u32)
+            }
+        }
+    }
+
+    fn pack_option(color: Option<Color>) -> Self {
+        color.map_or(Self::NONE, Self::pack)
+    }
+
+    fn pack_decoration(color: Option<&DecorationColor>) -> Self {
+        match color {
+            None => Self::NONE,
+            Some(DecorationColor::Rgb(r, g, b)) => Self::pack(Color::Rgb(*r, *g, *b)),
+            Some(DecorationColor::Named(name)) => {
+                Self::tagged(COLOR_THEME, Symbol::intern(name).index())
+            }
+        }
+    }
+
+    fn unpack(self) -> Option<Color> {
+        let value = self.0 & 0xFF_FFFF;
+        let [_, r, g, b] = value.to_be_bytes();
+        match self.0 >> 24 {
+            COLOR_RGB => Some(Color::Rgb(r, g, b)),
+            COLOR_INDEXED => Some(Color::Indexed(b)),
+            COLOR_NAMED => NAMED_COLORS.get(value as usize).copied(),
+            _ => None,
+        }
+    }
+
+    fn unpack_decoration(self) -> Option<DecorationColor> {
+        if self.0 >> 24 == COLOR_THEME {
+            let symbol = Symbol::from_index(self.0 & 0xFF_FFFF)?;
+            return Some(DecorationColor::Named(symbol.as_str().to_string()));
+        }
+        match self.unpack()? {
+            Color::Rgb(r, g, b) => Some(DecorationColor::Rgb(r, g, b)),
+            _ => None,
+        }
+    }
+}
+
+const FACE_UNDERLINE: u32 = 0;
+const FACE_BACKGROUND: u32 = 1;
+const FACE_FOREGROUND: u32 = 2;
+const FACE_STYLE: u32 = 3;
+const FACE_THEMED: u32 = 4;
+const FACE_KIND_MASK: u32 = 0b111;
+const UNDERLINE_STYLE_SHIFT: u32 = 3;
+const ADD_MODIFIER_SHIFT: u32 = 8;
+const SUB_MODIFIER_SHIFT: u32 = 20;
+const MODIFIER_MASK: u32 = 0xFFF;
+
+/// An [`OverlayFace`] in 16 bytes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct PackedFace {
+    /// Kind of face (bits 0-2), underline style (3-4), added modifiers
+    /// (8-19) and removed modifiers (20-31)
+    bits: u32,
+    fg: PackedColor,
+    bg: PackedColor,
+    underline: PackedColor,
+}
+
+impl PackedFace {
+    fn pack(face: &OverlayFace) -> Self {
+        let modifiers = |add: Modifier, sub: Modifier| {
+            ((u32::from(add.bits()) & MODIFIER_MASK) << ADD_MODIFIER_SHIFT)
+                | ((u32::from(sub.bits()) & MODIFIER_MASK) << SUB_MODIFIER_SHIFT)
+        };
+        let mut packed = Self {
+            bits: 0,
+            fg: PackedColor::NONE,
+            bg: PackedColor::NONE,
+            underline: PackedColor::NONE,
+        };
+        let kind = match face {
+            OverlayFace::Underline { color, style } => {
+                packed.bits = (*style as u32) << UNDERLINE_STYLE_SHIFT;
+                packed.fg = PackedColor::pack(*color);
+                FACE_UNDERLINE
+            }
+            OverlayFace::Background { color } => {
+                packed.bg = PackedColor::pack(*color);
+                FACE_BACKGROUND
+            }
+            OverlayFace::Foreground { color } => {
+                packed.fg = PackedColor::pack(*color);
+                FACE_FOREGROUND
+            }
+            OverlayFace::Style { style } => {
+                packed.bits = modifiers(style.add_modifier, style.sub_modifier);
+                packed.fg = PackedColor::pack_option(style.fg);
+                packed.bg = PackedColor::pack_option(style.bg);
+                packed.underline = PackedColor::pack_option(style.underline_color);
+                FACE_STYLE
+            }
+            OverlayFace::Themed { style } => {
+                packed.bits = modifiers(style.add_modifier, Modifier::empty());
+                packed.fg = PackedColor::pack_decoration(style.fg.as_ref());
+                packed.bg = PackedColor::pack_decoration(style.bg.as_ref());
+                FACE_THEMED
+            }
+        };
+        packed.bits |= kind;
+        packed
+    }
+
+    fn unpack(&self) -> OverlayFace {
+        let modifier = |shift: u32| {
+            Modifier::from_bits_truncate(((self.bits >> shift) & MODIFIER_MASK) as u16)
+        };
+        let color = |packed: PackedColor| packed.unpack().unwrap_or(Color::Reset);
+        match self.bits & FACE_KIND_MASK {
+            FACE_UNDERLINE => OverlayFace::Underline {
+                color: color(self.fg),
+                style: match (self.bits >> UNDERLINE_STYLE_SHIFT) & 0b11 {
+                    0 => UnderlineStyle::Straight,
+                    1 => UnderlineStyle::Wavy,
+                    2 => UnderlineStyle::Dotted,
+                    _ => UnderlineStyle::Dashed,
+                },
+            },
+            FACE_BACKGROUND => OverlayFace::Background {
+                color: color(self.bg),
+            },
+            FACE_FOREGROUND => OverlayFace::Foreground {
+                color: color(self.fg),
+            },
+            FACE_STYLE => OverlayFace::Style {
+                style: Style {
+                    fg: self.fg.unpack(),
+                    bg: self.bg.unpack(),
+                    underline_color: self.underline.unpack(),
+                    add_modifier: modifier(ADD_MODIFIER_SHIFT),
+                    sub_modifier: modifier(SUB_MODIFIER_SHIFT),
+                },
+            },
+            _ => OverlayFace::Themed {
+                style: ThemedStyle {
+                    fg: self.fg.unpack_decoration(),
+                    bg: self.bg.unpack_decoration(),
+                    add_modifier: modifier(ADD_MODIFIER_SHIFT),
+                },
+            },
+        }
+    }
+}
+
 /// Priority for overlay z-ordering
 /// Higher priority overlays are rendered on top of lower priority ones
 pub type Priority = i32;
@@ -114,8 +358,8 @@ pub struct Overlay {
     /// End marker (right affinity - moves after inserted text)
     pub end_marker: MarkerId,
 
-    /// Visual appearance of the overlay
-    pub face: OverlayFace,
+    /// Visual appearance of the overlay (see [`Overlay::face`])
+    face: PackedFace,
 
     /// Priority for z-ordering (higher = on top)
     pub priority: Priority,
@@ -146,7 +390,7 @@ impl Overlay {
             namespace: None,
             start_marker,
             end_marker,
-            face,
+            face: PackedFace::pack(&face),
             priority: 0,
             message: None,
             extend_to_line_end: false,
@@ -201,12 +445,36 @@ impl Overlay {
         self
     }
 
+    /// Visual appearance of the overlay
+    ///
+    /// Faces are stored packed; this unpacks a copy.
+    pub fn face(&self) -> OverlayFace {
+        self.face.unpack()
+    }
+
+    /// A copy of this overlay with theme colors looked up in `theme`
+    pub fn resolved(&self, theme: &Theme) -> Self {
+        let mut overlay = self.clone();
+        if let OverlayFace::Themed { style } = self.face() {
+            overlay.face = PackedFace::pack(&OverlayFace::Style {
+                style: style.resolve(theme),
+            });
+        }
+        overlay
+    }
+
     /// Get the current byte range by resolving markers
     /// This is called once per frame during rendering setup
     pub fn range(&self, marker_list: &MarkerList) -> Range<usize> {
-        let start = marker_list.get_position(self.start_marker).unwrap_or(0);
-        let end = marker_list.get_position(self.end_marker).unwrap_or(0);
-        start..end
+        self.start(marker_list)..self.end(marker_list)
+    }
+
+    fn start(&self, marker_list: &MarkerList) -> usize {
+        marker_list.get_position(self.start_marker).unwrap_or(0)
+    }
+
+    fn end(&self, marker_list: &MarkerList) -> usize {
+        marker_list.get_position(self.end_marker).unwrap_or(0)
     }
 
     /// Check if this overlay contains a position
@@ -221,28 +489,61 @@ impl Overlay {
     }
 }
 
+/// Marks an empty node of [`OverlayManager::reach`]
+const NO_OVERLAY: u32 = u32::MAX;
+
 /// Manages overlays for a buffer
-/// Overlays are sorted by priority for efficient rendering
+///
+/// Overlays are kept in the order of their start positions. Edits move
+/// markers without reordering them, so the order stays valid as the text
+/// changes: a range query is a binary search for the overlays starting
+/// inside the range, plus a walk down a segment tree of how far overlays
+/// reach for those starting before it. Each namespace's overlays are indexed
+/// for bulk removal.
 #[derive(Debug, Clone)]
 pub struct OverlayManager {
     /// All active overlays, indexed for O(1) lookup by handle
-    overlays: Vec<Overlay>,
+    overlays: HashMap<OverlayHandle, Overlay>,
+    /// Handles in the order of their overlays' start positions
+    by_start: Vec<OverlayHandle>,
+    /// Handles of each namespace's overlays
+    namespaces: HashMap<OverlayNamespace, HashSet<OverlayHandle>>,
+    /// Segment tree over `by_start` whose nodes hold the index of the overlay
+    /// ending furthest below them. Edits keep it valid like `by_start`;
+    /// adding or removing overlays drops it until the next query.
+    reach: OnceLock<Vec<u32>>,
 }
 
 impl OverlayManager {
     /// Create a new empty overlay manager
     pub fn new() -> Self {
         Self {
-            overlays: Vec::new(),
+            overlays: HashMap::new(),
+            by_start: Vec::new(),
+            namespaces: HashMap::new(),
+            reach: OnceLock::new(),
         }
     }
 
     /// Add an overlay and return its handle for later removal
-    pub fn add(&mut self, overlay: Overlay) -> OverlayHandle {
+    pub fn add(&mut self, overlay: Overlay, marker_list: &MarkerList) -> OverlayHandle {
         let handle = overlay.handle.clone();
-        self.overlays.push(overlay);
-        // Keep sorted by priority (ascending - lower priority first)
-        self.overlays.sort_by_key(|o| o.priority);
+        if let Some(index) = self.position(&handle, marker_list) {
+            self.detach(&[index]);
+        }
+        let start = overlay.start(marker_list);
+        let index = self
+            .by_start
+            .partition_point(|h| self.overlays[h].start(marker_list) <= start);
+        self.by_start.insert(index, handle.clone());
+        if let Some(namespace) = &overlay.namespace {
+            self.namespaces
+                .entry(namespace.clone())
+                .or_default()
+                .insert(handle.clone());
+        }
+        self.overlays.insert(handle.clone(), overlay);
+        self.reach.take();
         handle
     }
 
@@ -252,140 +553,138 @@ impl OverlayManager {
         handle: &OverlayHandle,
         marker_list: &mut MarkerList,
     ) -> bool {
-        if let Some(pos) = self.overlays.iter().position(|o| &o.handle == handle) {
-            let overlay = self.overlays.remove(pos);
-            marker_list.delete(overlay.start_marker);
-            marker_list.delete(overlay.end_marker);
-            true
-        } else {
-            false
+        match self.position(handle, marker_list) {
+            Some(index) => {
+                self.remove_at(vec![index], marker_list);
+                true
+            }
+            None => false,
         }
     }
 
     /// Remove all overlays in a namespace
     pub fn clear_namespace(&mut self, namespace: &OverlayNamespace, marker_list: &mut MarkerList) {
-        // Collect markers to delete
-        let markers_to_delete: Vec<_> = self
-            .overlays
-            .iter()
-            .filter(|o| o.namespace.as_ref() == Some(namespace))
-            .flat_map(|o| vec![o.start_marker, o.end_marker])
-            .collect();
-
-        // Remove overlays
-        self.overlays
-            .retain(|o| o.namespace.as_ref() != Some(namespace));
+        let Some(handles) = self.namespaces.remove(namespace) else {
+            return;
+        };
+        // One pass over the start order beats finding each overlay in it
+        let overlays = &mut self.overlays;
+        self.by_start.retain(|handle| {
+            if !handles.contains(handle) {
+                return true;
+            }
+            if let Some(overlay) = overlays.remove(handle) {
+                marker_list.delete(overlay.start_marker);
+                marker_list.delete(overlay.end_marker);
+            }
+            false
+        });
+        self.reach.take();
+    }
 
-        // Delete markers
-        for marker_id in markers_to_delete {
-            marker_list.delete(marker_id);
-        }
+    /// Remove the overlays of a namespace that overlap a range, including
+    /// ones that collapsed to an empty range inside it
+    pub fn clear_namespace_in_range(
+        &mut self,
+        namespace: &OverlayNamespace,
+        range: &Range<usize>,
+        marker_list: &mut MarkerList,
+    ) {
+        let indices: Vec<usize> = self
+            .matching(range, true, marker_list)
+            .into_iter()
+            .filter(|&index| {
+                self.overlays[&self.by_start[index]].namespace.as_ref() == Some(namespace)
+            })
+            .collect();
+        self.remove_at(indices, marker_list);
     }
 
     /// Remove all overlays in a range and clean up their markers
     pub fn remove_in_range(&mut self, range: &Range<usize>, marker_list: &mut MarkerList) {
-        // Collect markers to delete
-        let markers_to_delete: Vec<_> = self
-            .overlays
-            .iter()
-            .filter(|o| o.overlaps(range, marker_list))
-            .flat_map(|o| vec![o.start_marker, o.end_marker])
-            .collect();
-
-        // Remove overlays
-        self.overlays.retain(|o| !o.overlaps(range, marker_list));
-
-        // Delete markers
-        for marker_id in markers_to_delete {
-            marker_list.delete(marker_id);
-        }
+        let indices = self.matching(range, false, marker_list);
+        self.remove_at(indices, marker_list);
     }
 
     /// Clear all overlays and their markers
     pub fn clear(&mut self, marker_list: &mut MarkerList) {
         // Delete all markers
-        for overlay in &self.overlays {
+        for overlay in self.overlays.values() {
             marker_list.delete(overlay.start_marker);
             marker_list.delete(overlay.end_marker);
         }
 
         self.overlays.clear();
+        self.by_start.clear();
+        self.namespaces.clear();
+        self.reach.take();
     }
 
     /// Get all overlays at a specific position, sorted by priority
     pub fn at_position(&self, position: usize, marker_list: &MarkerList) -> Vec<&Overlay> {
-        self.overlays
-            .iter()
-            .filter(|o| {
-                let range = o.range(marker_list);
-                range.contains(&position)
-            })
-            .collect()
+        self.in_range(&(position..position.saturating_add(1)), marker_list)
     }
 
     /// Get all overlays that overlap with a range, sorted by priority
     pub fn in_range(&self, range: &Range<usize>, marker_list: &MarkerList) -> Vec<&Overlay> {
-        self.overlays
-            .iter()
-            .filter(|o| o.overlaps(range, marker_list))
-            .collect()
+        let mut overlays: Vec<&Overlay> = self
+            .matching(range, false, marker_list)
+            .into_iter()
+            .map(|index| &self.overlays[&self.by_start[index]])
+            .collect();
+        sort_by_priority(&mut overlays, |o| *o);
+        overlays
     }
 
-    /// Query overlays in a viewport range efficiently using the marker interval tree
+    /// Query overlays in a viewport range efficiently
     ///
     /// This is much faster than calling `at_position()` for every character in the range.
-    /// Returns overlays with their resolved byte ranges.
+    /// Returns overlays with their resolved byte ranges, sorted by priority.
     ///
     /// # Performance
     /// - Old approach: O(N * M) where N = positions to check, M = overlay count
-    /// - This approach: O(log M + k) where k = overlays in viewport (typically 2-10)
+    /// - This approach: O(log² M + k log² M) where k = overlays in viewport
     pub fn query_viewport(
         &self,
         start: usize,
         end: usize,
         marker_list: &MarkerList,
     ) -> Vec<(&Overlay, Range<usize>)> {
-        use std::collections::HashMap;
-
-        // Query the marker interval tree once for all markers in viewport
-        // This is O(log N + k) where k = markers in viewport
-        let visible_markers = marker_list.query_range(start, end);
-
-        // Build a quick lookup map: marker_id -> position
-        let marker_positions: HashMap<_, _> = visible_markers
+        let mut overlays: Vec<(&Overlay, Range<usize>)> = self
+            .matching(&(start..end), false, marker_list)
             .into_iter()
-            .map(|(id, start, _end)| (id, start))
+            .map(|index| {
+                let overlay = &self.overlays[&self.by_start[index]];
+                (overlay, overlay.range(marker_list))
+            })
             .collect();
+        sort_by_priority(&mut overlays, |(o, _)| *o);
+        overlays
+    }
 
-        // Find overlays whose markers are in the viewport
-        // Only resolve positions for overlays that are actually visible
-        self.overlays
-            .iter()
-            .filter_map(|overlay| {
-                // Try to get positions from our viewport query results
-                let start_pos = marker_positions.get(&overlay.start_marker)?;
-                let end_pos = marker_positions.get(&overlay.end_marker)?;
-
-                let range = *start_pos..*end_pos;
-
-                // Only include if actually overlaps viewport
-                if range.start < end && range.end > start {
-                    Some((overlay, range))
-                } else {
-                    None
-                }
-            })
-            .collect()
+    /// The overlays of a namespace, in no particular order
+    pub fn in_namespace<'a>(
+        &'a self,
+        namespace: &OverlayNamespace,
+    ) -> impl Iterator<Item = &'a Overlay> + 'a {
+        self.namespaces
+            .get(namespace)
+            .into_iter()
+            .flatten()
+            .filter_map(|handle| self.overlays.get(handle))
     }
 
     /// Get overlay by handle
     pub fn get_by_handle(&self, handle: &OverlayHandle) -> Option<&Overlay> {
-        self.overlays.iter().find(|o| &o.handle == handle)
+        self.overlays.get(handle)
     }
 
     /// Get mutable overlay by handle
+    ///
+    /// Its namespace and markers must stay as they are, since the overlay is
+    /// indexed by them.
     pub fn get_by_handle_mut(&mut self, handle: &OverlayHandle) -> Option<&mut Overlay> {
-        self.overlays.iter_mut().find(|o| &o.handle == handle)
+        self.overlays.get_mut(handle)
     }
 
     /// Get total number of overlays
@@ -398,10 +697,288 @@ impl OverlayManager {
         self.overlays.is_empty()
     }
 
-    /// Get all overlays (for rendering)
-    pub fn all(&self) -> &[Overlay] {
-        &self.overlays
+    /// Every overlay, in the order of their start positions
+    pub fn iter(&self) -> impl Iterator<Item = &Overlay> {
+        self.by_start.iter().map(|handle| &self.overlays[handle])
+    }
+
+    /// Get all overlays, sorted by priority
+    pub fn all(&self) -> Vec<&Overlay> {
+        let mut overlays: Vec<&Overlay> = self.iter().collect();
+        sort_by_priority(&mut overlays, |o| *o);
+        overlays
+    }
+
+    /// Estimated heap memory used by the overlays, not counting their markers
+    pub fn memory_bytes(&self) -> usize {
+        use std::mem::size_of;
+        // Hash tables take a control byte per bucket besides the entry
+        let table = self.overlays.capacity() * (size_of::<(OverlayHandle, Overlay)>() + 1);
+        let messages: usize = self
+            .overlays
+            .values()
+            .filter_map(|o| o.message.as_ref())
+            .map(String::capacity)
+            .sum();
+        let order = self.by_start.capacity() * size_of::<OverlayHandle>();
+        let namespaces: usize = self
+            .namespaces
+            .values()
+            .map(|handles| {
+                size_of::<(OverlayNamespace, HashSet<OverlayHandle>)>()
+                    + handles.capacity() * (size_of::<OverlayHandle>() + 1)
+            })
+            .sum();
+        let reach = self
+            .reach
+            .get()
+            .map_or(0, |tree| tree.capacity() * size_of::<u32>());
+        table + messages + order + namespaces + reach
+    }
+
+    /// Give back the capacity the indexes kept from overlays since removed
+    pub fn shrink_to_fit(&mut self) {
+        self.overlays.shrink_to_fit();
+        self.by_start.shrink_to_fit();
+        self.namespaces.retain(|_, handles| !handles.is_empty());
+        self.namespaces.shrink_to_fit();
+        for handles in self.namespaces.values_mut() {
+            handles.shrink_to_fit();
+        }
+        self.reach = OnceLock::new();
+    }
+
+    /// Check that every overlay's markers exist and span a non-inverted range
+    /// within a buffer of `buffer_len` bytes, and that the indexes agree
+    pub fn check_invariants(
+        &self,
+        marker_list: &MarkerList,
+        buffer_len: usize,
+    ) -> Result<(), String> {
+        for overlay in self.overlays.values() {
+            let handle = &overlay.handle;
+            let (Some(start), Some(end)) = (
+                marker_list.get_position(overlay.start_marker),
+                marker_list.get_position(overlay.end_marker),
+            ) else {
+                return Err(format!(
+                    "overlay {} refers to deleted markers {:?}/{:?}",
+                    handle, overlay.start_marker, overlay.end_marker
+                ));
+            };
+            if start > end {
+                return Err(format!(
+                    "overlay {} has inverted range {}..{}",
+                    handle, start, end
+                ));
+            }
+            if end > buffer_len {
+                return Err(format!(
+                    "overlay {} range {}..{} is past the end of the buffer ({} bytes)",
+                    handle, start, end, buffer_len
+                ));
+            }
+            let indexed = match &overlay.namespace {
+                Some(namespace) => self
+                    .namespaces
+                    .get(namespace)
+                    .is_some_and(|handles| handles.contains(handle)),
+                None => true,
+            };
+            if !indexed {
+                return Err(format!("overlay {} is missing from its namespace", handle));
+            }
+        }
+        if self.by_start.len() != self.overlays.len()
+            || self.by_start.iter().any(|h| !self.overlays.contains_key(h))
+        {
+            return Err(format!(
+                "{} overlays but {} in start order",
+                self.overlays.len(),
+                self.by_start.len()
+            ));
+        }
+        let starts: Vec<usize> = self.iter().map(|o| o.start(marker_list)).collect();
+        if let Some(i) = starts.windows(2).position(|pair| pair[0] > pair[1]) {
+            return Err(format!(
+                "overlay {} starts at {}, after the next one at {}",
+                self.by_start[i],
+                starts[i],
+                starts[i + 1]
+            ));
+        }
+        let namespaced: usize = self.namespaces.values().map(HashSet::len).sum();
+        if namespaced
+            != self
+                .overlays
+                .values()
+                .filter(|o| o.namespace.is_some())
+                .count()
+        {
+            return Err("namespace index lists removed overlays".to_string());
+        }
+        Ok(())
+    }
+
+    /// Index in `by_start` of an overlay
+    fn position(&self, handle: &OverlayHandle, marker_list: &MarkerList) -> Option<usize> {
+        let start = self.overlays.get(handle)?.start(marker_list);
+        let first = self
+            .by_start
+            .partition_point(|h| self.overlays[h].start(marker_list) < start);
+        // Overlays whose markers were deleted under them are out of place
+        self.by_start[first..]
+            .iter()
+            .position(|h| h == handle)
+            .map(|i| first + i)
+            .or_else(|| self.by_start.iter().position(|h| h == handle))
     }
+
+    /// Indices in `by_start` of the overlays overlapping `range`, and with
+    /// `starting_inside`, of those starting inside it even if empty
+    fn matching(
+        &self,
+        range: &Range<usize>,
+        starting_inside: bool,
+        marker_list: &MarkerList,
+    ) -> Vec<usize> {
+        let starting_before = |position: usize| {
+            self.by_start
+                .partition_point(|h| self.overlays[h].start(marker_list) < position)
+        };
+        let inside_from = starting_before(range.start);
+        let inside_to = starting_before(range.end);
+
+        let mut found = Vec::new();
+        let reach = self.reach(marker_list);
+        self.reaching(
+            reach,
+            1,
+            0..reach.len() / 2,
+            inside_from.min(inside_to),
+            range.start,
+            marker_list,
+            &mut found,
+        );
+        found.extend(
+            (inside_from..inside_to)
+                .filter(|&index| starting_inside || self.end_at(index, marker_list) > range.start),
+        );
+        found
+    }
+
+    /// Collect the indices below `limit` under `node` of the overlays ending
+    /// after `position`
+    #[allow(clippy::too_many_arguments)]
+    fn reaching(
+        &self,
+        reach: &[u32],
+        node: usize,
+        leaves: Range<usize>,
+        limit: usize,
+        position: usize,
+        marker_list: &MarkerList,
+        found: &mut Vec<usize>,
+    ) {
+        let furthest = reach[node];
+        if leaves.start >= limit
+            || furthest == NO_OVERLAY
+            || self.end_at(furthest as usize, marker_list) <= position
+        {
+            return;
+        }
+        if leaves.len() == 1 {
+            found.push(furthest as usize);
+            return;
+        }
+        let mid = leaves.start + leaves.len() / 2;
+        for (child, leaves) in [
+            (2 * node, leaves.start..mid),
+            (2 * node + 1, mid..leaves.end),
+        ] {
+            self.reaching(reach, child, leaves, limit, position, marker_list, found);
+        }
+    }
+
+    /// The segment tree of how far overlays reach, built if needed
+    fn reach(&self, marker_list: &MarkerList) -> &[u32] {
+        self.reach.get_or_init(|| {
+            let ends: Vec<usize> = (0..self.by_start.len())
+                .map(|index| self.end_at(index, marker_list))
+                .collect();
+            let size = ends.len().next_power_of_two();
+            let mut tree = vec![NO_OVERLAY; 2 * size];
+            for index in 0..ends.len() {
+                tree[size + index] = index as u32;
+            }
+            for node in (1..size).rev() {
+                let (left, right) = (tree[2 * node], tree[2 * node + 1]);
+                tree[node] = if left == NO_OVERLAY
+                    || (right != NO_OVERLAY && ends[right as usize] > ends[left as usize])
+                {
+                    right
+                } else {
+                    left
+                };
+            }
+            tree
+        })
+    }
+
+    fn end_at(&self, index: usize, marker_list: &MarkerList) -> usize {
+        self.overlays[&self.by_start[index]].end(marker_list)
+    }
+
+    /// Remove the overlays at these indices of `by_start`, and their markers
+    fn remove_at(&mut self, indices: Vec<usize>, marker_list: &mut MarkerList) {
+        for overlay in self.detach(&indices) {
+            marker_list.delete(overlay.start_marker);
+            marker_list.delete(overlay.end_marker);
+        }
+    }
+
+    /// Take the overlays at these indices of `by_start` out of the indexes
+    fn detach(&mut self, indices: &[usize]) -> Vec<Overlay> {
+        if indices.is_empty() {
+            return Vec::new();
+        }
+        let mut indices = indices.to_vec();
+        indices.sort_unstable();
+        indices.dedup();
+        let mut detached = Vec::with_capacity(indices.len());
+        for &index in &indices {
+            let handle = &self.by_start[index];
+            let Some(overlay) = self.overlays.remove(handle) else {
+                continue;
+            };
+            if let Some(namespace) = &overlay.namespace {
+                if let Some(handles) = self.namespaces.get_mut(namespace) {
+                    handles.remove(handle);
+                    if handles.is_empty() {
+                        self.namespaces.remove(namespace);
+                    }
+                }
+            }
+            detached.push(overlay);
+        }
+        let mut removed = indices.into_iter().peekable();
+        let mut index = 0;
+        self.by_start.retain(|_| {
+            let keep = removed.next_if_eq(&index).is_none();
+            index += 1;
+            keep
+        });
+        self.reach.take();
+        detached
+    }
+}
+
+/// Sort overlays by priority, then by when they were added
+fn sort_by_priority<T>(items: &mut [T], overlay: impl Fn(&T) -> &Overlay) {
+    items.sort_by(|a, b| {
+        let (a, b) = (overlay(a), overlay(b));
+        (a.priority, &a.handle).cmp(&(b.priority, &b.handle))
+    });
 }
 
 impl Default for OverlayManager {
@@ -581,7 +1158,7 @@ mod tests {
             OverlayFace::Background { color: Color::Red },
         );
 
-        let handle = manager.add(overlay);
+        let handle = manager.add(overlay, &marker_list);
         assert_eq!(manager.len(), 1);
 
         manager.remove_by_handle(&handle, &mut marker_list);
@@ -618,9 +1195,9 @@ mod tests {
             },
         );
 
-        manager.add(overlay1);
-        manager.add(overlay2);
-        manager.add(overlay3);
+        manager.add(overlay1, &marker_list);
+        manager.add(overlay2, &marker_list);
+        manager.add(overlay3, &marker_list);
         assert_eq!(manager.len(), 3);
 
         // Clear only the namespace
@@ -628,32 +1205,66 @@ mod tests {
         assert_eq!(manager.len(), 1); // Only overlay3 remains
     }
 
+    #[test]
+    fn test_overlay_namespace_clear_in_range() {
+        let mut marker_list = MarkerList::new();
+        marker_list.set_buffer_size(100);
+        let mut manager = OverlayManager::new();
+
+        let ns = OverlayNamespace::from_string("todo".to_string());
+        let face = OverlayFace::Background { color: Color::Red };
+        for range in [5..10, 15..20, 25..25] {
+            let overlay =
+                Overlay::with_namespace(&mut marker_list, range, face.clone(), ns.clone());
+            manager.add(overlay, &marker_list);
+        }
+        manager.add(Overlay::new(&mut marker_list, 16..18, face), &marker_list);
+
+        // Overlapping and collapsed namespace overlays go, others stay
+        manager.clear_namespace_in_range(&ns, &(12..30), &mut marker_list);
+        let remaining: Vec<_> = manager
+            .all()
+            .iter()
+            .map(|o| (o.range(&marker_list), o.namespace.is_some()))
+            .collect();
+        assert_eq!(remaining, vec![(5..10, true), (16..18, false)]);
+    }
+
     #[test]
     fn test_overlay_priority_sorting() {
         let mut marker_list = MarkerList::new();
         marker_list.set_buffer_size(100);
         let mut manager = OverlayManager::new();
 
-        manager.add(Overlay::with_priority(
-            &mut marker_list,
-            5..10,
-            OverlayFace::Background { color: Color::Red },
-            10,
-        ));
-        manager.add(Overlay::with_priority(
-            &mut marker_list,
-            5..10,
-            OverlayFace::Background { color: Color::Blue },
-            5,
-        ));
-        manager.add(Overlay::with_priority(
-            &mut marker_list,
-            5..10,
-            OverlayFace::Background {
-                color: Color::Green,
-            },
-            15,
-        ));
+        manager.add(
+            Overlay::with_priority(
+                &mut marker_list,
+                5..10,
+                OverlayFace::Background { color: Color::Red },
+                10,
+            ),
+            &marker_list,
+        );
+        manager.add(
+            Overlay::with_priority(
+                &mut marker_list,
+                5..10,
+                OverlayFace::Background { color: Color::Blue },
+                5,
+            ),
+            &marker_list,
+        );
+        manager.add(
+            Overlay::with_priority(
+                &mut marker_list,
+                5..10,
+                OverlayFace::Background {
+                    color: Color::Green,
+                },
+                15,
+            ),
+            &marker_list,
+        );
 
         let overlays = manager.at_position(7, &marker_list);
         assert_eq!(overlays.len(), 3);
@@ -685,4 +1296,330 @@ mod tests {
         assert!(overlay.overlaps(&(15..25), &marker_list));
         assert!(!overlay.overlaps(&(20..30), &marker_list));
     }
+
+    #[test]
+    fn test_overlay_manager_check_invariants() {
+        let mut marker_list = MarkerList::new();
+        marker_list.set_buffer_size(100);
+        let mut manager = OverlayManager::new();
+
+        let overlay = Overlay::new(
+            &mut marker_list,
+            10..20,
+            OverlayFace::Background { color: Color::Red },
+        );
+        let handle = overlay.handle.clone();
+        let start_marker = overlay.start_marker;
+        manager.add(overlay, &marker_list);
+        assert!(manager.check_invariants(&marker_list, 100).is_ok());
+
+        let err = manager.check_invariants(&marker_list, 15).unwrap_err();
+        assert!(err.contains(&handle.to_string()), "{}", err);
+        assert!(err.contains("past the end"), "{}", err);
+
+        marker_list.delete(start_marker);
+        let err = manager.check_invariants(&marker_list, 100).unwrap_err();
+        assert!(err.contains("deleted markers"), "{}", err);
+    }
+
+    #[test]
+    fn test_overlay_handle_and_namespace_strings() {
+        let handle = OverlayHandle::new();
+        let text = handle.to_string();
+        assert!(text.starts_with("ovl_"), "{}", text);
+        assert_eq!(OverlayHandle::from_string(text.clone()), handle);
+        assert_eq!(
+            serde_json::to_string(&handle).unwrap(),
+            format!("\"{}\"", text)
+        );
+
+        // Other strings round-trip as they are
+        for text in ["test-overlay", "ovl_007", "ovl_"] {
+            let handle = OverlayHandle::from_string(text.to_string());
+            assert_eq!(handle.to_string(), text);
+        }
+
+        let ns: OverlayNamespace = serde_json::from_str("\"lint\"").unwrap();
+        assert_eq!(ns, OverlayNamespace::from_string("lint".to_string()));
+        assert_eq!(ns.as_str(), "lint");
+        assert_eq!(serde_json::to_string(&ns).unwrap(), "\"lint\"");
+    }
+
+    #[test]
+    fn test_packed_faces_round_trip() {
+        use crate::view::theme::DecorationColor;
+
+        let faces = [
+            OverlayFace::Underline {
+                color: Color::LightRed,
+                style: UnderlineStyle::Dashed,
+            },
+            OverlayFace::Background {
+                color: Color::Rgb(1, 2, 3),
+            },
+            OverlayFace::Foreground {
+                color: Color::Indexed(200),
+            },
+            OverlayFace::Style {
+                style: Style::default()
+                    .fg(Color::White)
+                    .underline_color(Color::Rgb(9, 8, 7))
+                    .add_modifier(Modifier::BOLD | Modifier::ITALIC)
+                    .remove_modifier(Modifier::DIM),
+            },
+            OverlayFace::Style {
+                style: Style::default(),
+            },
+            OverlayFace::Themed {
+                style: ThemedStyle {
+                    fg: Some(DecorationColor::Named("diagnostic.error_fg".to_string())),
+                    bg: Some(DecorationColor::Rgb(10, 20, 30)),
+                    add_modifier: Modifier::UNDERLINED,
+                },
+            },
+        ];
+        assert_eq!(std::mem::size_of::<PackedFace>(), 16);
+        for face in faces {
+            assert_eq!(PackedFace::pack(&face).unpack(), face);
+        }
+    }
+
+    #[test]
+    fn test_overlays_spanning_the_viewport_are_found() {
+        let mut marker_list = MarkerList::new();
+        marker_list.set_buffer_size(1000);
+        let mut manager = OverlayManager::new();
+        let face = OverlayFace::Background { color: Color::Red };
+        for range in [0..900, 100..110, 300..310, 500..510, 480..490] {
+            let overlay = Overlay::new(&mut marker_list, range, face.clone());
+            manager.add(overlay, &marker_list);
+        }
+
+        let ranges: Vec<_> = manager
+            .query_viewport(295, 505, &marker_list)
+            .into_iter()
+            .map(|(_, range)| range)
+            .collect();
+        assert_eq!(ranges, vec![0..900, 300..310, 500..510, 480..490]);
+
+        // Edits move the overlays without reordering them
+        marker_list.adjust_for_delete(0, 305);
+        marker_list.adjust_for_insert(10, 50);
+        assert!(manager.check_invariants(&marker_list, 745).is_ok());
+        assert_eq!(manager.at_position(1, &marker_list).len(), 2);
+        let starts: Vec<_> = manager
+            .iter()
+            .map(|o| o.range(&marker_list).start)
+            .collect();
+        assert_eq!(starts, vec![0, 0, 0, 225, 245]);
+    }
+
+    #[test]
+    fn test_namespace_index_follows_removals() {
+        let mut marker_list = MarkerList::new();
+        marker_list.set_buffer_size(100);
+        let mut manager = OverlayManager::new();
+        let ns = OverlayNamespace::from_string("lint".to_string());
+        let face = OverlayFace::Background { color: Color::Red };
+        let mut handles = Vec::new();
+        for start in [10, 20, 30] {
+            let overlay = Overlay::with_namespace(
+                &mut marker_list,
+                start..start + 5,
+                face.clone(),
+                ns.clone(),
+            );
+            handles.push(manager.add(overlay, &marker_list));
+        }
+        manager.add(Overlay::new(&mut marker_list, 0..50, face), &marker_list);
+
+        assert!(manager.remove_by_handle(&handles[1], &mut marker_list));
+        manager.remove_in_range(&(30..31), &mut marker_list);
+        assert_eq!(manager.in_namespace(&ns).count(), 1);
+        assert!(manager.check_invariants(&marker_list, 100).is_ok());
+
+        manager.clear_namespace(&ns, &mut marker_list);
+        assert_eq!(manager.in_namespace(&ns).count(), 0);
+        assert!(manager.is_empty());
+        assert_eq!(marker_list.marker_count(), 0);
+        assert!(manager.memory_bytes() > 0);
+    }
+
+    mod prop_tests {
+        use super::*;
+        use proptest::prelude::*;
+
+        /// The store as it was before it was indexed: a Vec kept in priority
+        /// order and scanned by every query
+        #[derive(Default)]
+        struct NaiveOverlays {
+            overlays: Vec<Overlay>,
+        }
+
+        impl NaiveOverlays {
+            fn add(&mut self, overlay: Overlay) {
+                self.overlays.push(overlay);
+                self.overlays.sort_by_key(|o| o.priority);
+            }
+
+            fn remove_where(
+                &mut self,
+                marker_list: &mut MarkerList,
+                remove: impl Fn(&Overlay, &MarkerList) -> bool,
+            ) {
+                let (removed, kept) = self
+                    .overlays
+                    .drain(..)
+                    .partition(|o| remove(o, marker_list));
+                self.overlays = kept;
+                for overlay in removed {
+                    marker_list.delete(overlay.start_marker);
+                    marker_list.delete(overlay.end_marker);
+                }
+            }
+
+            fn in_range(&self, range: &Range<usize>, marker_list: &MarkerList) -> Vec<String> {
+                self.overlays
+                    .iter()
+                    .filter(|o| o.overlaps(range, marker_list))
+                    .map(|o| o.handle.to_string())
+                    .collect()
+            }
+        }
+
+        #[derive(Debug, Clone)]
+        enum Op {
+            Add {
+                start: usize,
+                len: usize,
+                namespace: Option<u8>,
+                priority: i32,
+            },
+            Remove(usize),
+            ClearNamespace(u8),
+            ClearNamespaceInRange(u8, usize, usize),
+            RemoveInRange(usize, usize),
+            Insert(usize, usize),
+            Delete(usize, usize),
+        }
+
+        fn arb_op() -> impl Strategy<Value = Op> {
+            prop_oneof![
+                4 => (0..300usize, 0..40usize, prop::option::of(0..3u8), -2..3i32).prop_map(
+                    |(start, len, namespace, priority)| Op::Add {
+                        start,
+                        len,
+                        namespace,
+                        priority,
+                    }
+                ),
+                1 => (0..50usize).prop_map(Op::Remove),
+                1 => (0..3u8).prop_map(Op::ClearNamespace),
+                1 => (0..3u8, 0..300usize, 0..60usize)
+                    .prop_map(|(ns, start, len)| Op::ClearNamespaceInRange(ns, start, len)),
+                1 => (0..300usize, 0..60usize).prop_map(|(start, len)| Op::RemoveInRange(start, len)),
+                1 => (0..300usize, 1..30usize).prop_map(|(pos, len)| Op::Insert(pos, len)),
+                1 => (0..300usize, 1..30usize).prop_map(|(pos, len)| Op::Delete(pos, len)),
+            ]
+        }
+
+        fn namespace(n: u8) -> OverlayNamespace {
+            OverlayNamespace::from_string(format!("prop-{}", n))
+        }
+
+        proptest! {
+            /// The indexed store answers range queries like the naive one
+            /// after any mix of additions, removals, clears and edits
+            #[test]
+            fn prop_store_matches_naive(
+                ops in prop::collection::vec(arb_op(), 1..60),
+                queries in prop::collection::vec((0..320usize, 0..80usize), 1..8),
+            ) {
+                let mut buffer_len = 300;
+                let mut markers = MarkerList::new();
+                let mut naive_markers = MarkerList::new();
+                let mut store = OverlayManager::new();
+                let mut naive = NaiveOverlays::default();
+                let face = OverlayFace::Background { color: Color::Red };
+
+                for op in ops {
+                    match op {
+                        Op::Add { start, len, namespace: ns, priority } => {
+                            let start = start.min(buffer_len);
+                            let range = start..(start + len).min(buffer_len);
+                            let mut overlay = Overlay::with_priority(
+                                &mut markers, range.clone(), face.clone(), priority);
+                            overlay.namespace = ns.map(namespace);
+                            let mut copy = overlay.clone();
+                            copy.start_marker = naive_markers.create(range.start, true);
+                            copy.end_marker = naive_markers.create(range.end, false);
+                            store.add(overlay, &markers);
+                            naive.add(copy);
+                        }
+                        Op::Remove(nth) => {
+                            if !naive.overlays.is_empty() {
+                                let handle = naive.overlays[nth % naive.overlays.len()].handle.clone();
+                                prop_assert!(store.remove_by_handle(&handle, &mut markers));
+                                naive.remove_where(&mut naive_markers, |o, _| o.handle == handle);
+                            }
+                        }
+                        Op::ClearNamespace(n) => {
+                            let ns = namespace(n);
+                            store.clear_namespace(&ns, &mut markers);
+                            naive.remove_where(&mut naive_markers, |o, _| o.namespace.as_ref() == Some(&ns));
+                        }
+                        Op::ClearNamespaceInRange(n, start, len) => {
+                            let ns = namespace(n);
+                            let range = start..start + len;
+                            store.clear_namespace_in_range(&ns, &range, &mut markers);
+                            naive.remove_where(&mut naive_markers, |o, m| {
+                                o.namespace.as_ref() == Some(&ns)
+                                    && (o.overlaps(&range, m) || range.contains(&o.range(m).start))
+                            });
+                        }
+                        Op::RemoveInRange(start, len) => {
+                            let range = start..start + len;
+                            store.remove_in_range(&range, &mut markers);
+                            naive.remove_where(&mut naive_markers, |o, m| o.overlaps(&range, m));
+                        }
+                        Op::Insert(pos, len) => {
+                            let pos = pos.min(buffer_len);
+                            markers.adjust_for_insert(pos, len);
+                            naive_markers.adjust_for_insert(pos, len);
+                            buffer_len += len;
+                        }
+                        Op::Delete(pos, len) => {
+                            let pos = pos.min(buffer_len);
+                            let len = len.min(buffer_len - pos);
+                            if len > 0 {
+                                markers.adjust_for_delete(pos, len);
+                                naive_markers.adjust_for_delete(pos, len);
+                                buffer_len -= len;
+                            }
+                        }
+                    }
+
+                    prop_assert!(store.check_invariants(&markers, buffer_len).is_ok(),
+                        "{:?}", store.check_invariants(&markers, buffer_len));
+                    prop_assert_eq!(store.len(), naive.overlays.len());
+                    for &(start, len) in &queries {
+                        let range = start..start + len;
+                        let expected = naive.in_range(&range, &naive_markers);
+                        let found: Vec<String> = store
+                            .query_viewport(range.start, range.end, &markers)
+                            .into_iter()
+                            .map(|(o, _)| o.handle.to_string())
+                            .collect();
+                        prop_assert_eq!(&found, &expected, "query {:?}", range);
+                        let at: Vec<String> = store
+                            .at_position(start, &markers)
+                            .into_iter()
+                            .map(|o| o.handle.to_string())
+                            .collect();
+                        prop_assert_eq!(at, naive.in_range(&(start..start + 1), &naive_markers));
+                    }
+                }
+            }
+        }
+    }
 }
@@ -6,6 +6,7 @@ use ratatui::{
     Frame,
 };
 
+use super::glyphs::glyphs;
 use super::markdown::{parse_markdown, wrap_styled_lines, wrap_text_lines, StyledLine};
 use super::ui::scrollbar::{render_scrollbar, ScrollbarColors, ScrollbarState};
 
@@ -385,7 +386,7 @@ impl Popup {
                 .width
                 .saturating_sub(border_width)
                 .saturating_sub(scrollbar_reserved) as usize;
-            let desc_vec = vec![desc.clone()];
+            let desc_vec: Vec<String> = desc.lines().map(str::to_string).collect();
             let wrapped = wrap_text_lines(&desc_vec, content_width.saturating_sub(2));
             wrapped.len() as u16 + 1 // +1 for blank line after description
         } else {
@@ -394,7 +395,7 @@ impl Popup {
     }
 
     /// Calculate the actual content height based on the popup content
-    fn content_height(&self) -> u16 {
+    pub(crate) fn content_height(&self) -> u16 {
         // Use the popup's configured width for wrapping calculation
         self.content_height_for_width(self.width)
     }
@@ -410,7 +411,7 @@ impl Popup {
 
         // Calculate description height if present
         let description_lines = if let Some(desc) = &self.description {
-            let desc_vec = vec![desc.clone()];
+            let desc_vec: Vec<String> = desc.lines().map(str::to_string).collect();
             let wrapped = wrap_text_lines(&desc_vec, content_width.saturating_sub(2));
             wrapped.len() as u16 + 1 // +1 for blank line after description
         } else {
@@ -438,7 +439,7 @@ impl Popup {
 
     /// Calculate the area where this popup should be rendered
     pub fn calculate_area(&self, terminal_area: Rect, cursor_pos: Option<(u16, u16)>) -> Rect {
-        match self.position {
+        let area = match self.position {
             PopupPosition::AtCursor | PopupPosition::BelowCursor | PopupPosition::AboveCursor => {
                 let (cursor_x, cursor_y) =
                     cursor_pos.unwrap_or((terminal_area.width / 2, terminal_area.height / 2));
@@ -540,7 +541,9 @@ impl Popup {
                     height,
                 }
             }
-        }
+        };
+        // A cursor position from before a resize can be off screen
+        clamp_rect_to_bounds(area, terminal_area)
     }
 
     /// Render the popup to the frame
@@ -571,6 +574,7 @@ impl Popup {
         let block = if self.bordered {
             let mut block = Block::default()
                 .borders(Borders::ALL)
+                .border_set(glyphs().border_set())
                 .border_style(self.border_style)
                 .style(self.background_style);
 
@@ -591,7 +595,7 @@ impl Popup {
         if let Some(desc) = &self.description {
             // Word-wrap description to fit inner width
             let desc_wrap_width = inner_area.width.saturating_sub(2) as usize; // Leave some padding
-            let desc_vec = vec![desc.clone()];
+            let desc_vec: Vec<String> = desc.lines().map(str::to_string).collect();
             let wrapped_desc = wrap_text_lines(&desc_vec, desc_wrap_width);
             let desc_lines: usize = wrapped_desc.len();
 
@@ -1027,6 +1031,24 @@ mod tests {
         assert_eq!(area.y, 47);
     }
 
+    #[test]
+    fn test_popup_at_stale_cursor_stays_on_screen() {
+        let theme = crate::view::theme::Theme::dark();
+        let popup = Popup::text(vec!["test".to_string()], &theme)
+            .with_width(30)
+            .with_max_height(10)
+            .with_position(PopupPosition::AtCursor);
+
+        // Cursor position from before the terminal shrank to 20x5
+        let terminal_area = Rect::new(0, 0, 20, 5);
+        let area = popup.calculate_area(terminal_area, Some((90, 40)));
+        assert_eq!(area.intersection(terminal_area), area);
+        assert!(area.width <= 20 && area.height <= 5);
+
+        let area = popup.calculate_area(Rect::new(0, 0, 1, 1), Some((90, 40)));
+        assert!(area.x == 0 && area.y == 0 && area.width <= 1 && area.height <= 1);
+    }
+
     #[test]
     fn test_clamp_rect_to_bounds() {
         let bounds = Rect {
@@ -5,7 +5,7 @@
 
 use super::popup::PopupManager;
 use crate::input::handler::{DeferredAction, InputContext, InputHandler, InputResult};
-use crossterm::event::{KeyCode, KeyEvent};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 
 impl InputHandler for PopupManager {
     fn handle_key_event(&mut self, event: &KeyEvent, ctx: &mut InputContext) -> InputResult {
@@ -25,6 +25,14 @@ impl InputHandler for PopupManager {
                 InputResult::Consumed
             }
 
+            // Completion popups take j and k as typing rather than navigation
+            KeyCode::Char(c @ ('j' | 'k'))
+                if event.modifiers.is_empty() && self.is_completion_popup() =>
+            {
+                ctx.defer(DeferredAction::PopupTypeChar(c));
+                InputResult::Consumed
+            }
+
             // Selection navigation
             KeyCode::Up | KeyCode::Char('k') if event.modifiers.is_empty() => {
                 if let Some(popup) = self.top_mut() {
@@ -78,7 +86,7 @@ impl InputHandler for PopupManager {
             }
 
             // Type-to-filter for completion popups
-            KeyCode::Char(c) if event.modifiers.is_empty() => {
+            KeyCode::Char(c) if event.modifiers.difference(KeyModifiers::SHIFT).is_empty() => {
                 // Check if this is a completion popup that supports type-to-filter
                 if self.is_completion_popup() {
                     ctx.defer(DeferredAction::PopupTypeChar(c));
@@ -109,7 +117,6 @@ mod tests {
     use super::*;
     use crate::view::popup::{Popup, PopupListItem};
     use crate::view::theme::Theme;
-    use crossterm::event::KeyModifiers;
 
     fn key(code: KeyCode) -> KeyEvent {
         KeyEvent::new(code, KeyModifiers::NONE)
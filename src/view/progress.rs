@@ -0,0 +1,258 @@
+//! Progress of long operations
+//!
+//! Plugins (through `editor.startProgress`) and the editor itself (saves
+//! that continue in the background) report long work here, so it shows in
+//! one place: a single entry as a segment of the status bar, several as
+//! notifications. An entry shows its percentage, or a spinner while the
+//! percentage is unknown, and a cancellable one can be cancelled by the user.
+
+use crate::view::glyphs::glyphs;
+use rust_i18n::t;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::{Duration, Instant};
+
+/// How long a plugin's entry may go without an update before it is
+/// finished for it
+pub const STALE_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Time per spinner frame
+pub const SPINNER_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Id for a new progress entry
+pub fn next_progress_id() -> u32 {
+    static NEXT_ID: AtomicU32 = AtomicU32::new(1);
+    NEXT_ID.fetch_add(1, Ordering::Relaxed)
+}
+
+/// Who reports an entry's progress
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProgressOwner {
+    Editor,
+    Plugin(String),
+}
+
+/// A long operation in progress
+#[derive(Debug, Clone)]
+pub struct ProgressEntry {
+    pub id: u32,
+    pub owner: ProgressOwner,
+    pub title: String,
+    pub message: Option<String>,
+    /// None while the amount of work left is unknown
+    pub percentage: Option<u8>,
+    pub cancellable: bool,
+    /// The user cancelled it and the owner hasn't finished it yet
+    pub cancelling: bool,
+    updated_at: Instant,
+}
+
+impl ProgressEntry {
+    /// Whether the user can still cancel it
+    pub fn can_cancel(&self) -> bool {
+        self.cancellable && !self.cancelling
+    }
+
+    /// One line describing the entry: percentage or spinner frame, title,
+    /// message
+    pub fn label(&self, frame: usize) -> String {
+        let mut label = match self.percentage {
+            Some(percentage) => format!("{}% {}", percentage, self.title),
+            None => {
+                let frames: Vec<char> = glyphs().spinner.chars().collect();
+                let spinner = frames.get(frame % frames.len().max(1)).copied();
+                format!("{} {}", spinner.unwrap_or(' '), self.title)
+            }
+        };
+        if let Some(message) = self.message.as_deref().filter(|m| !m.is_empty()) {
+            label.push_str(": ");
+            label.push_str(message);
+        }
+        if self.cancelling {
+            label.push(' ');
+            label.push_str(&t!("progress.cancelling"));
+        }
+        label
+    }
+}
+
+/// The entries in progress, oldest first
+#[derive(Debug, Default)]
+pub struct ProgressList {
+    entries: Vec<ProgressEntry>,
+    /// When the spinner started turning
+    spinner_epoch: Option<Instant>,
+}
+
+impl ProgressList {
+    pub fn start(
+        &mut self,
+        id: u32,
+        owner: ProgressOwner,
+        title: String,
+        cancellable: bool,
+        now: Instant,
+    ) {
+        self.entries.retain(|entry| entry.id != id);
+        self.spinner_epoch.get_or_insert(now);
+        self.entries.push(ProgressEntry {
+            id,
+            owner,
+            title,
+            message: None,
+            percentage: None,
+            cancellable,
+            cancelling: false,
+            updated_at: now,
+        });
+    }
+
+    /// Change the percentage and/or message of an entry
+    ///
+    /// Any update, even one changing nothing, keeps the entry from going
+    /// stale. Returns false if there is no such entry.
+    pub fn update(
+        &mut self,
+        id: u32,
+        percentage: Option<u8>,
+        message: Option<String>,
+        now: Instant,
+    ) -> bool {
+        let Some(entry) = self.entries.iter_mut().find(|entry| entry.id == id) else {
+            return false;
+        };
+        if let Some(percentage) = percentage {
+            entry.percentage = Some(percentage.min(100));
+        }
+        if message.is_some() {
+            entry.message = message;
+        }
+        entry.updated_at = now;
+        true
+    }
+
+    pub fn finish(&mut self, id: u32) -> Option<ProgressEntry> {
+        let index = self.entries.iter().position(|entry| entry.id == id)?;
+        let entry = self.entries.remove(index);
+        if self.entries.is_empty() {
+            self.spinner_epoch = None;
+        }
+        Some(entry)
+    }
+
+    /// Mark an entry as cancelling, if the user can cancel it
+    pub fn cancel(&mut self, id: u32) -> Option<&ProgressEntry> {
+        let entry = self
+            .entries
+            .iter_mut()
+            .find(|entry| entry.id == id && entry.can_cancel())?;
+        entry.cancelling = true;
+        Some(entry)
+    }
+
+    pub fn get(&self, id: u32) -> Option<&ProgressEntry> {
+        self.entries.iter().find(|entry| entry.id == id)
+    }
+
+    pub fn entries(&self) -> &[ProgressEntry] {
+        &self.entries
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Plugin entries not updated for `timeout`
+    ///
+    /// The editor's own entries are finished by the operations themselves.
+    pub fn stale(&self, now: Instant, timeout: Duration) -> Vec<u32> {
+        self.entries
+            .iter()
+            .filter(|entry| matches!(entry.owner, ProgressOwner::Plugin(_)))
+            .filter(|entry| now.saturating_duration_since(entry.updated_at) >= timeout)
+            .map(|entry| entry.id)
+            .collect()
+    }
+
+    /// Spinner frame to draw at `now`
+    pub fn spinner_frame(&self, now: Instant) -> usize {
+        self.spinner_epoch.map_or(0, |epoch| {
+            (now.saturating_duration_since(epoch).as_millis() / SPINNER_INTERVAL.as_millis())
+                as usize
+        })
+    }
+
+    /// Whether any entry shows the spinner
+    pub fn spinning(&self) -> bool {
+        self.entries.iter().any(|entry| entry.percentage.is_none())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn plugin(name: &str) -> ProgressOwner {
+        ProgressOwner::Plugin(name.to_string())
+    }
+
+    #[test]
+    fn test_update_and_finish() {
+        let now = Instant::now();
+        let mut list = ProgressList::default();
+        list.start(1, plugin("indexer"), "Indexing".to_string(), true, now);
+        assert_eq!(
+            list.get(1).unwrap().label(0).split_once(' ').unwrap().1,
+            "Indexing"
+        );
+
+        assert!(list.update(1, Some(40), None, now));
+        assert!(list.update(1, None, Some("src/main.rs".to_string()), now));
+        assert_eq!(list.get(1).unwrap().label(0), "40% Indexing: src/main.rs");
+        assert!(list.update(1, Some(250), None, now));
+        assert_eq!(list.get(1).unwrap().percentage, Some(100));
+
+        assert!(list.finish(1).is_some());
+        assert!(list.is_empty());
+        assert!(!list.update(1, Some(50), None, now));
+    }
+
+    #[test]
+    fn test_cancel_only_once_and_only_cancellable() {
+        let now = Instant::now();
+        let mut list = ProgressList::default();
+        list.start(1, plugin("a"), "A".to_string(), true, now);
+        list.start(2, ProgressOwner::Editor, "B".to_string(), false, now);
+
+        assert!(list.cancel(1).is_some());
+        assert!(list.get(1).unwrap().cancelling);
+        assert!(list.cancel(1).is_none());
+        assert!(list.cancel(2).is_none());
+    }
+
+    #[test]
+    fn test_stale_plugin_entries() {
+        let now = Instant::now();
+        let mut list = ProgressList::default();
+        list.start(1, plugin("a"), "A".to_string(), false, now);
+        list.start(2, plugin("b"), "B".to_string(), false, now);
+        list.start(3, ProgressOwner::Editor, "C".to_string(), false, now);
+
+        let later = now + STALE_TIMEOUT;
+        list.update(2, Some(10), None, later);
+        assert_eq!(list.stale(later, STALE_TIMEOUT), vec![1]);
+    }
+
+    #[test]
+    fn test_spinner_turns_while_percentage_unknown() {
+        let now = Instant::now();
+        let mut list = ProgressList::default();
+        list.start(1, plugin("a"), "A".to_string(), false, now);
+        assert!(list.spinning());
+        assert_eq!(list.spinner_frame(now + SPINNER_INTERVAL * 3), 3);
+        let entry = list.get(1).unwrap();
+        assert_ne!(entry.label(0), entry.label(1));
+
+        list.update(1, Some(5), None, now);
+        assert!(!list.spinning());
+    }
+}
@@ -29,8 +29,14 @@ pub enum PromptType {
     QueryReplaceConfirm,
     /// Execute a command by name (M-x)
     Command,
-    /// Go to a specific line number
+    /// Go to a specific line number (or `:50%` / `:byte N`)
     GotoLine,
+    /// Go to a byte offset
+    GotoByteOffset,
+    /// Go to a percentage of the buffer
+    GotoPercentage,
+    /// Go to a key path (`spec.containers[0]`) in a JSON, YAML or TOML file
+    GotoDataPath,
     /// Choose an ANSI background file
     SetBackgroundFile,
     /// Set background blend ratio (0-1)
@@ -74,8 +80,33 @@ pub enum PromptType {
     CopyWithFormattingTheme,
     /// Confirm reverting a modified file
     ConfirmRevert,
+    /// Confirm "Revert Buffer to Saved" for a modified buffer, with the diff
+    /// preview buffer if one is open
+    ConfirmRevertToSaved {
+        buffer_id: crate::model::event::BufferId,
+        preview: Option<crate::model::event::BufferId>,
+    },
     /// Confirm saving over a file that changed on disk
     ConfirmSaveConflict,
+    /// Confirm writing a file that couldn't be saved with `files.sudo_save_command`
+    ConfirmSudoSave {
+        buffer_id: crate::model::event::BufferId,
+    },
+    /// Confirm unlocking a file matching `files.protected_globs`
+    ConfirmUnlockProtected {
+        buffer_id: crate::model::event::BufferId,
+    },
+    /// Confirm the first save of a protected file since it was unlocked
+    ConfirmProtectedSave {
+        buffer_id: crate::model::event::BufferId,
+    },
+    /// Confirm reloading a buffer whose file changed on disk (`files.auto_revert: "ask"`)
+    ConfirmReloadChanged {
+        buffer_id: crate::model::event::BufferId,
+    },
+    /// Confirm opening a buffer for a file that doesn't exist
+    /// (`files.missing_files: "ask"`)
+    ConfirmCreateFile { path: std::path::PathBuf },
     /// Confirm overwriting an existing file during SaveAs
     ConfirmOverwriteFile { path: std::path::PathBuf },
     /// Confirm closing a modified buffer (save/discard/cancel)
@@ -85,6 +116,22 @@ pub enum PromptType {
     },
     /// Confirm quitting with modified buffers
     ConfirmQuitWithModified,
+    /// Confirm quitting while files are still being saved in the background
+    ConfirmQuitWithSavesRunning,
+    /// Pick a session snapshot to restore (select from list)
+    RestoreSessionSnapshot,
+    /// Confirm discarding modified buffers to restore a session snapshot
+    ConfirmRestoreSessionSnapshot { path: std::path::PathBuf },
+    /// Name to save the current split layout under
+    SaveLayoutAs,
+    /// Pick a named layout to switch to (select from list)
+    SwitchLayout,
+    /// Pick a named layout to delete (select from list)
+    DeleteLayout,
+    /// Pick a recently used project to switch to (select from list)
+    OpenRecentProject,
+    /// Confirm dropping a recent project whose directory no longer exists
+    ConfirmRemoveRecentProject { path: std::path::PathBuf },
     /// File Explorer rename operation
     /// Stores the original path and name for the file/directory being renamed
     FileExplorerRename {
@@ -98,10 +145,58 @@ pub enum PromptType {
     },
     /// Switch to a tab by name (from the current split's open buffers)
     SwitchToTab,
+    /// Pick an open buffer to compare the active one with
+    CompareBufferWith,
+    /// First of the two directories to compare
+    CompareDirectoriesLeft,
+    /// Directory to compare `left` with
+    CompareDirectoriesRight { left: std::path::PathBuf },
+    /// Code action kind to run on many files
+    BatchCodeActionKind,
+    /// Files to run the code action `kind` on
+    BatchCodeActionScope { kind: String },
+    /// Parameter `index` of the plugin command named `command`, after the
+    /// `values` of the ones before it
+    CommandArgument {
+        command: String,
+        index: usize,
+        values: serde_json::Map<String, serde_json::Value>,
+    },
+    /// Paste an entry of the clipboard history (select from list)
+    PasteFromHistory,
+    /// Filter the keyboard shortcuts view (applied as the user types)
+    KeyboardShortcutsFilter,
+    /// File to export the usage statistics to
+    ExportUsageStats,
+    /// File to write a settings archive to
+    ExportSettingsArchive,
+    /// Settings archive to import
+    ImportSettingsArchive,
+    /// Least severe level shown in the editor log
+    EditorLogLevel,
     /// Run shell command on buffer/selection
     /// If replace is true, replace the input with the output
     /// If replace is false, output goes to a new buffer
     ShellCommand { replace: bool },
+    /// Pick a file whose contents to insert at the cursors
+    InsertFile,
+    /// Confirm inserting a file larger than the large file threshold
+    ConfirmInsertLargeFile { path: std::path::PathBuf },
+    /// Command whose output to insert at the cursors; with
+    /// `selection_as_stdin` each cursor's selection is filtered through it
+    InsertCommandOutput { selection_as_stdin: bool },
+    /// Options for "Sort Lines" (descending, numeric, ignore case)
+    SortLines,
+    /// Confirm applying a text transform to the whole buffer, as nothing
+    /// is selected
+    ConfirmTransformBuffer {
+        transform: crate::primitives::text_transform::TextTransform,
+    },
+    /// Character or string to line up with "Align Selections by Character"
+    AlignSelections,
+    /// Whether to drop the undo history of the active buffer, or of all
+    /// buffers, or how many undo steps to keep
+    CompactBuffers { all: bool },
 }
 
 /// Prompt state for the minibuffer
@@ -15,6 +15,10 @@ impl InputHandler for Prompt {
 
         match event.code {
             // Confirmation and cancellation
+            KeyCode::Enter if ctrl => {
+                ctx.defer(DeferredAction::ConfirmPromptDryRun);
+                InputResult::Consumed
+            }
             KeyCode::Enter => {
                 ctx.defer(DeferredAction::ConfirmPrompt);
                 InputResult::Consumed
@@ -355,6 +359,21 @@ mod tests {
             .any(|a| matches!(a, DeferredAction::ConfirmPrompt)));
     }
 
+    #[test]
+    fn test_prompt_ctrl_enter_confirms_dry_run() {
+        let mut prompt = Prompt::new("Test: ".to_string(), PromptType::Command);
+        let mut ctx = InputContext::new();
+
+        prompt.handle_key_event(
+            &KeyEvent::new(KeyCode::Enter, KeyModifiers::CONTROL),
+            &mut ctx,
+        );
+        assert!(ctx
+            .deferred_actions
+            .iter()
+            .any(|a| matches!(a, DeferredAction::ConfirmPromptDryRun)));
+    }
+
     #[test]
     fn test_prompt_escape_cancels() {
         let mut prompt = Prompt::new("Test: ".to_string(), PromptType::Search);
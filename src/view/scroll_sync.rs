@@ -181,7 +181,13 @@ impl ScrollSyncManager {
     }
 
     /// Create a new scroll sync group and return its ID
+    ///
+    /// IDs plugins already chose with [`Self::create_group_with_id`] are
+    /// skipped.
     pub fn create_group(&mut self, left_split: SplitId, right_split: SplitId) -> ScrollSyncGroupId {
+        while self.groups.iter().any(|g| g.id == self.next_id) {
+            self.next_id += 1;
+        }
         let id = self.next_id;
         self.next_id += 1;
 
@@ -358,4 +364,12 @@ mod tests {
         assert_eq!(group.left_scroll_line(), 55);
         assert_eq!(group.right_scroll_line(), 65); // 60 + 5
     }
+
+    #[test]
+    fn test_create_group_skips_plugin_ids() {
+        let mut manager = ScrollSyncManager::new();
+        assert!(manager.create_group_with_id(1, SplitId(1), SplitId(2)));
+        assert_eq!(manager.create_group(SplitId(3), SplitId(4)), 2);
+        assert!(!manager.create_group_with_id(2, SplitId(5), SplitId(6)));
+    }
 }
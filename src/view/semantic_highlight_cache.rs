@@ -61,6 +61,7 @@ impl SemanticHighlightCache {
     /// * `viewport_end` - End byte offset of visible viewport
     /// * `context_bytes` - Number of bytes before/after viewport to parse for context
     /// * `highlight_color` - Color to use for highlights
+    /// * `now` - Current time on the editor's clock
     ///
     /// # Returns
     /// A tuple of (highlights, needs_redraw) where needs_redraw is true if
@@ -74,8 +75,8 @@ impl SemanticHighlightCache {
         viewport_end: usize,
         context_bytes: usize,
         highlight_color: Color,
+        now: Instant,
     ) -> &[HighlightSpan] {
-        let now = Instant::now();
         let viewport = (viewport_start, viewport_end);
 
         // First call - compute immediately
@@ -148,9 +149,9 @@ impl SemanticHighlightCache {
     ///
     /// Returns Some(remaining_time) if a redraw should be scheduled,
     /// None if no redraw is needed.
-    pub fn needs_redraw(&self) -> Option<Duration> {
+    pub fn needs_redraw(&self, now: Instant) -> Option<Duration> {
         self.cursor_changed_at.map(|changed_at| {
-            let elapsed = changed_at.elapsed();
+            let elapsed = now.duration_since(changed_at);
             if elapsed >= self.debounce_delay {
                 Duration::ZERO
             } else {
@@ -194,7 +195,6 @@ mod tests {
     use super::*;
     use crate::model::buffer::Buffer;
     use crate::primitives::semantic_highlight::SemanticHighlighter;
-    use std::thread::sleep;
 
     #[test]
     fn test_raw_highlighter_works() {
@@ -215,9 +215,10 @@ mod tests {
         let mut highlighter = SemanticHighlighter::new();
         let buffer = Buffer::from_str_test("hello world hello");
         let color = Color::Rgb(60, 60, 80);
+        let now = Instant::now();
 
         // First call - should compute immediately
-        let spans = cache.get_highlights(&mut highlighter, &buffer, 0, 0, 17, 1000, color);
+        let spans = cache.get_highlights(&mut highlighter, &buffer, 0, 0, 17, 1000, color, now);
         assert!(!spans.is_empty(), "Should compute on first call");
     }
 
@@ -227,14 +228,15 @@ mod tests {
         let mut highlighter = SemanticHighlighter::new();
         let buffer = Buffer::from_str_test("hello world hello");
         let color = Color::Rgb(60, 60, 80);
+        let now = Instant::now();
 
         // First call at position 0 (on "hello") - computes
-        let spans = cache.get_highlights(&mut highlighter, &buffer, 0, 0, 17, 1000, color);
+        let spans = cache.get_highlights(&mut highlighter, &buffer, 0, 0, 17, 1000, color, now);
         assert!(!spans.is_empty(), "Should compute on first call");
         let first_len = spans.len();
 
         // Move cursor to position 6 (on "world") - returns stale cache
-        let spans = cache.get_highlights(&mut highlighter, &buffer, 6, 0, 17, 1000, color);
+        let spans = cache.get_highlights(&mut highlighter, &buffer, 6, 0, 17, 1000, color, now);
         assert_eq!(
             spans.len(),
             first_len,
@@ -243,7 +245,7 @@ mod tests {
 
         // Check that we need a redraw
         assert!(
-            cache.needs_redraw().is_some(),
+            cache.needs_redraw(now).is_some(),
             "Should signal need for redraw"
         );
     }
@@ -254,20 +256,20 @@ mod tests {
         let mut highlighter = SemanticHighlighter::new();
         let buffer = Buffer::from_str_test("hello world hello");
         let color = Color::Rgb(60, 60, 80);
+        let now = Instant::now();
 
         // First call at position 0 (on "hello")
-        let spans = cache.get_highlights(&mut highlighter, &buffer, 0, 0, 17, 1000, color);
+        let spans = cache.get_highlights(&mut highlighter, &buffer, 0, 0, 17, 1000, color, now);
         let first_count = spans.len();
         assert!(!spans.is_empty(), "Should compute on first call");
 
         // Move to position 6 (on "world")
-        let _ = cache.get_highlights(&mut highlighter, &buffer, 6, 0, 17, 1000, color);
+        let _ = cache.get_highlights(&mut highlighter, &buffer, 6, 0, 17, 1000, color, now);
 
-        // Wait for debounce
-        sleep(Duration::from_millis(20));
-
-        // Same position - should compute now (world has only 1 occurrence)
-        let spans = cache.get_highlights(&mut highlighter, &buffer, 6, 0, 17, 1000, color);
+        // Same position once the debounce is over - should compute now
+        // (world has only 1 occurrence)
+        let later = now + Duration::from_millis(20);
+        let spans = cache.get_highlights(&mut highlighter, &buffer, 6, 0, 17, 1000, color, later);
         assert!(!spans.is_empty(), "Should have highlights after debounce");
         // "hello" appears twice, "world" appears once
         assert!(
@@ -282,16 +284,17 @@ mod tests {
         let mut highlighter = SemanticHighlighter::new();
         let buffer = Buffer::from_str_test("hello world hello");
         let color = Color::Rgb(60, 60, 80);
+        let now = Instant::now();
 
         // Populate cache
-        let spans = cache.get_highlights(&mut highlighter, &buffer, 0, 0, 17, 1000, color);
+        let spans = cache.get_highlights(&mut highlighter, &buffer, 0, 0, 17, 1000, color, now);
         assert!(!spans.is_empty());
 
         // Invalidate
         cache.invalidate();
 
         // Next call should recompute (first call after invalidation)
-        let spans = cache.get_highlights(&mut highlighter, &buffer, 0, 0, 17, 1000, color);
+        let spans = cache.get_highlights(&mut highlighter, &buffer, 0, 0, 17, 1000, color, now);
         assert!(!spans.is_empty(), "Should recompute after invalidation");
     }
 
@@ -301,21 +304,22 @@ mod tests {
         let mut highlighter = SemanticHighlighter::new();
         let buffer = Buffer::from_str_test("hello world");
         let color = Color::Rgb(60, 60, 80);
+        let now = Instant::now();
 
         // Initially no redraw needed
-        assert!(cache.needs_redraw().is_none());
+        assert!(cache.needs_redraw(now).is_none());
 
         // First call
-        let _ = cache.get_highlights(&mut highlighter, &buffer, 0, 0, 11, 1000, color);
+        let _ = cache.get_highlights(&mut highlighter, &buffer, 0, 0, 11, 1000, color, now);
 
         // No redraw needed after first compute
-        assert!(cache.needs_redraw().is_none());
+        assert!(cache.needs_redraw(now).is_none());
 
         // Move cursor
-        let _ = cache.get_highlights(&mut highlighter, &buffer, 6, 0, 11, 1000, color);
+        let _ = cache.get_highlights(&mut highlighter, &buffer, 6, 0, 11, 1000, color, now);
 
         // Now needs redraw
-        let remaining = cache.needs_redraw();
+        let remaining = cache.needs_redraw(now);
         assert!(remaining.is_some());
         assert!(remaining.unwrap() <= Duration::from_millis(50));
     }
@@ -6,6 +6,7 @@
 use super::items::{build_item_from_value, control_to_value, SettingControl, SettingItem};
 use super::schema::{SettingSchema, SettingType};
 use crate::view::controls::{FocusState, TextInputState};
+use crate::view::ui::LineEdit;
 use serde_json::Value;
 
 /// State for the entry detail dialog
@@ -160,7 +161,7 @@ impl EntryDialogState {
         if let Some(item) = self.items.first() {
             if item.path == "__key__" {
                 if let SettingControl::Text(state) = &item.control {
-                    return state.value.clone();
+                    return state.value().to_string();
                 }
             }
         }
@@ -460,7 +461,7 @@ impl EntryDialogState {
                 SettingControl::Map(s) => s.focus = state,
                 SettingControl::ObjectArray(s) => s.focus = state,
                 SettingControl::Json(s) => s.focus = state,
-                SettingControl::Complex { .. } => {}
+                SettingControl::Complex { .. } | SettingControl::ReadOnly { .. } => {}
             }
         }
     }
@@ -575,13 +576,21 @@ impl EntryDialogState {
         self.scroll_offset = new_offset.min(max_scroll);
     }
 
+    /// Single-line edit state of the current control, if it is a Text field
+    pub fn text_line_edit_mut(&mut self) -> Option<&mut LineEdit> {
+        match &mut self.current_item_mut()?.control {
+            SettingControl::Text(state) => Some(&mut state.edit),
+            _ => None,
+        }
+    }
+
     /// Start text editing mode for the current control
     pub fn start_editing(&mut self) {
         if let Some(item) = self.current_item_mut() {
             match &mut item.control {
                 SettingControl::Text(state) => {
-                    // TextInputState uses focus state, cursor is already at end from with_value
-                    state.cursor = state.value.len();
+                    state.edit.move_end();
+                    state.edit.begin_edit();
                     self.editing_text = true;
                 }
                 SettingControl::TextList(state) => {
@@ -609,6 +618,10 @@ impl EntryDialogState {
                 SettingControl::Number(state) => {
                     state.cancel_editing();
                 }
+                SettingControl::Text(state) => {
+                    state.edit.clear_selection();
+                    state.edit.end_edit();
+                }
                 _ => {}
             }
         }
@@ -6,6 +6,7 @@
 use super::items::SettingControl;
 use super::state::{FocusPanel, SettingsState};
 use crate::input::handler::{DeferredAction, InputContext, InputHandler, InputResult};
+use crate::view::ui::LineEdit;
 use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 
 /// Button action in entry dialog
@@ -118,6 +119,13 @@ impl SettingsState {
             return InputResult::Consumed;
         };
 
+        // Text fields handle the full set of single-line editing keys
+        if let Some(edit) = dialog.text_line_edit_mut() {
+            if handle_line_edit_key(edit, event, ctx) {
+                return InputResult::Consumed;
+            }
+        }
+
         match event.code {
             KeyCode::Esc => {
                 // Escape accepts changes (same as Tab) - exit editing mode
@@ -645,8 +653,22 @@ impl SettingsState {
     fn handle_text_editing_input(
         &mut self,
         event: &KeyEvent,
-        _ctx: &mut InputContext,
+        ctx: &mut InputContext,
     ) -> InputResult {
+        let ctrl = event.modifiers.contains(KeyModifiers::CONTROL);
+
+        if ctrl && matches!(event.code, KeyCode::Char('v') | KeyCode::Char('V')) {
+            ctx.defer(DeferredAction::PasteToSettings);
+            return InputResult::Consumed;
+        }
+
+        // Single-line fields (Text, Map add-new) handle the full set of editing keys
+        if let Some(edit) = self.text_line_edit_mut() {
+            if handle_line_edit_key(edit, event, ctx) {
+                return InputResult::Consumed;
+            }
+        }
+
         match event.code {
             KeyCode::Esc => {
                 // Check if current text field requires JSON validation
@@ -660,7 +682,7 @@ impl SettingsState {
                 self.text_add_item();
                 InputResult::Consumed
             }
-            KeyCode::Char(c) => {
+            KeyCode::Char(c) if !ctrl => {
                 self.text_insert(c);
                 InputResult::Consumed
             }
@@ -863,7 +885,7 @@ impl SettingsState {
                         self.open_edit_array_item_dialog();
                     }
                 }
-                SettingControl::Complex { .. } => {
+                SettingControl::Complex { .. } | SettingControl::ReadOnly { .. } => {
                     // Not editable via simple controls
                 }
             }
@@ -935,6 +957,27 @@ impl SettingsState {
     }
 }
 
+/// Apply an editing or clipboard key to a single-line settings field.
+///
+/// Ctrl+C/Ctrl+X put the selection on the editor clipboard; paste is handled
+/// by the caller. Returns true if the key was handled.
+fn handle_line_edit_key(edit: &mut LineEdit, event: &KeyEvent, ctx: &mut InputContext) -> bool {
+    if event.modifiers.contains(KeyModifiers::CONTROL) {
+        let clipboard_text = match event.code {
+            KeyCode::Char('c') | KeyCode::Char('C') => Some(edit.copy()),
+            KeyCode::Char('x') | KeyCode::Char('X') => Some(edit.cut()),
+            _ => None,
+        };
+        if let Some(text) = clipboard_text {
+            if let Some(text) = text {
+                ctx.defer(DeferredAction::CopyFromSettings(text));
+            }
+            return true;
+        }
+    }
+    edit.handle_key(event).is_some()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
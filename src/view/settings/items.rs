@@ -249,6 +249,12 @@ pub enum SettingControl {
     Complex {
         type_name: String,
     },
+    /// Stored value the control for its type can't show (e.g. a string where the
+    /// schema expects a boolean), displayed as raw JSON and left untouched
+    ReadOnly {
+        value: String,
+        reason: String,
+    },
 }
 
 impl SettingControl {
@@ -297,6 +303,8 @@ impl SettingControl {
                 // 1 for label + displayed lines
                 1 + state.display_height() as u16
             }
+            // ReadOnly needs: 1 value line + 1 explanation line
+            SettingControl::ReadOnly { .. } => 2,
             // All other controls fit in 1 line
             _ => 1,
         }
@@ -916,7 +924,7 @@ pub fn control_to_value(control: &SettingControl) -> serde_json::Value {
             })
             .unwrap_or(serde_json::Value::Null),
 
-        SettingControl::Text(state) => serde_json::Value::String(state.value.clone()),
+        SettingControl::Text(state) => serde_json::Value::String(state.value().to_string()),
 
         SettingControl::TextList(state) => {
             let arr: Vec<serde_json::Value> = state
@@ -937,6 +945,10 @@ pub fn control_to_value(control: &SettingControl) -> serde_json::Value {
         }
 
         SettingControl::Complex { .. } => serde_json::Value::Null,
+
+        SettingControl::ReadOnly { value, .. } => {
+            serde_json::from_str(value).unwrap_or(serde_json::Value::Null)
+        }
     }
 }
 
@@ -1021,7 +1033,7 @@ mod tests {
         assert!(item.modified);
 
         if let SettingControl::Text(state) = &item.control {
-            assert_eq!(state.value, "monokai");
+            assert_eq!(state.value(), "monokai");
         } else {
             panic!("Expected text control");
         }
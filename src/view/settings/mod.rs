@@ -6,6 +6,8 @@
 //! ## Architecture
 //!
 //! - `schema.rs` - Parse JSON Schema into setting definitions
+//! - `reconcile.rs` - Check a saved config against the schema (renamed, retyped
+//!   and unknown settings)
 //! - `items.rs` - Convert schema to renderable items with control states
 //! - `state.rs` - Manage settings UI state and pending changes
 //! - `render.rs` - Render the settings modal
@@ -17,6 +19,7 @@ pub mod input;
 pub mod items;
 pub mod layout;
 pub mod mouse;
+pub mod reconcile;
 pub mod render;
 pub mod schema;
 pub mod search;
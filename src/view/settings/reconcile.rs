@@ -0,0 +1,408 @@
+//! Reconciling a saved config with the current schema
+//!
+//! A config file written by an older (or newer) version of the editor can hold
+//! settings the current schema renamed, retyped or doesn't know at all. Before the
+//! settings UI shows the user config, every key in it is checked against the schema:
+//!
+//! - Keys with an entry in [`SETTING_MIGRATIONS`] are converted to their current
+//!   path and type, and written back on the next save.
+//! - Keys the schema doesn't know are listed as unrecognized settings, which the
+//!   user can keep (they are written back untouched) or delete.
+//! - Values of the wrong type are shown read-only instead of being replaced by
+//!   the default.
+
+use super::schema::{SettingCategory, SettingSchema, SettingType};
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+
+/// A setting that moved to a new path and/or changed type
+#[derive(Debug, Clone, Copy)]
+pub struct SettingMigration {
+    /// JSON pointer of the old setting
+    pub from: &'static str,
+    /// JSON pointer of the current setting (the same as `from` for a retype)
+    pub to: &'static str,
+    /// Convert an old value, or None if it isn't in the old form
+    pub convert: fn(&Value) -> Option<Value>,
+}
+
+/// Renamed and retyped settings, oldest first.
+///
+/// Add an entry here whenever a change to the config schema would make an
+/// existing config file invalid.
+pub const SETTING_MIGRATIONS: &[SettingMigration] = &[
+    SettingMigration {
+        from: "/editor/tabSize",
+        to: "/editor/tab_size",
+        convert: unchanged,
+    },
+    SettingMigration {
+        from: "/editor/lineNumbers",
+        to: "/editor/line_numbers",
+        convert: unchanged,
+    },
+    // `files.auto_revert` as a boolean, before the "ask" mode
+    SettingMigration {
+        from: "/files/auto_revert",
+        to: "/files/auto_revert",
+        convert: auto_revert_from_bool,
+    },
+];
+
+fn unchanged(value: &Value) -> Option<Value> {
+    Some(value.clone())
+}
+
+fn auto_revert_from_bool(value: &Value) -> Option<Value> {
+    let mode = if value.as_bool()? { "on" } else { "off" };
+    Some(Value::String(mode.to_string()))
+}
+
+/// A setting converted by a migration
+#[derive(Debug, Clone, PartialEq)]
+pub struct MigratedSetting {
+    /// Path the value was stored under
+    pub from: String,
+    /// Current path of the setting
+    pub path: String,
+    /// The converted value
+    pub value: Value,
+}
+
+/// A key the current schema doesn't know
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnrecognizedSetting {
+    /// JSON pointer of the key
+    pub path: String,
+    /// The stored value
+    pub value: Value,
+    /// Whether the key is written back on save (false = deleted)
+    pub keep: bool,
+}
+
+/// A known setting whose stored value has the wrong type
+#[derive(Debug, Clone, PartialEq)]
+pub struct MismatchedSetting {
+    /// JSON pointer of the setting
+    pub path: String,
+    /// The stored value
+    pub value: Value,
+    /// JSON type the schema expects (e.g. "boolean")
+    pub expected: &'static str,
+}
+
+/// Result of checking a user config against the schema
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Reconciliation {
+    pub migrated: Vec<MigratedSetting>,
+    pub unrecognized: Vec<UnrecognizedSetting>,
+    pub mismatched: Vec<MismatchedSetting>,
+}
+
+impl Reconciliation {
+    /// Whether the config matched the schema
+    pub fn is_empty(&self) -> bool {
+        self.migrated.is_empty() && self.unrecognized.is_empty() && self.mismatched.is_empty()
+    }
+
+    /// The stored values the typed config can't hold and that must be written
+    /// back as they are: kept unrecognized keys and mismatched values.
+    pub fn preserved_values(&self) -> Value {
+        let mut preserved = Value::Object(Default::default());
+        let kept = self
+            .unrecognized
+            .iter()
+            .filter(|s| s.keep)
+            .map(|s| (&s.path, &s.value));
+        let mismatched = self.mismatched.iter().map(|s| (&s.path, &s.value));
+        for (path, value) in kept.chain(mismatched) {
+            insert_at_pointer(&mut preserved, path, value.clone());
+        }
+        preserved
+    }
+}
+
+/// Check a user config (as written in the file) against the schema
+pub fn reconcile(categories: &[SettingCategory], user_config: &Value) -> Reconciliation {
+    let index = SchemaIndex::new(categories);
+    let mut result = Reconciliation::default();
+    let mut handled = HashSet::new();
+
+    for migration in SETTING_MIGRATIONS {
+        let Some(old) = user_config.pointer(migration.from) else {
+            continue;
+        };
+        // A renamed setting that is also set under its new name is left alone
+        if migration.from != migration.to && user_config.pointer(migration.to).is_some() {
+            continue;
+        }
+        if let Some(value) = (migration.convert)(old) {
+            handled.insert(migration.from.to_string());
+            result.migrated.push(MigratedSetting {
+                from: migration.from.to_string(),
+                path: migration.to.to_string(),
+                value,
+            });
+        }
+    }
+
+    index.walk(user_config, "", &handled, &mut result);
+    result
+}
+
+/// Whether a stored value has the JSON type a setting expects.
+///
+/// Null always matches: it stands for "unset" in optional settings.
+pub fn value_matches(setting_type: &SettingType, value: &Value) -> bool {
+    if value.is_null() {
+        return true;
+    }
+    match setting_type {
+        SettingType::Boolean => value.is_boolean(),
+        SettingType::Integer { .. } => value.is_i64() || value.is_u64(),
+        SettingType::Number { .. } => value.is_number(),
+        // Enums stay open to values added by other versions (e.g. theme names)
+        SettingType::String | SettingType::Enum { .. } => value.is_string(),
+        SettingType::StringArray => value
+            .as_array()
+            .is_some_and(|items| items.iter().all(Value::is_string)),
+        SettingType::ObjectArray { .. } => value.is_array(),
+        SettingType::Object { .. } | SettingType::Map { .. } => value.is_object(),
+        SettingType::Complex => true,
+    }
+}
+
+/// Name of the JSON type a setting expects, for messages
+pub fn expected_type_name(setting_type: &SettingType) -> &'static str {
+    match setting_type {
+        SettingType::Boolean => "boolean",
+        SettingType::Integer { .. } => "integer",
+        SettingType::Number { .. } => "number",
+        SettingType::String | SettingType::Enum { .. } => "string",
+        SettingType::StringArray | SettingType::ObjectArray { .. } => "array",
+        SettingType::Object { .. } | SettingType::Map { .. } => "object",
+        SettingType::Complex => "value",
+    }
+}
+
+/// Settings and categories of the schema by path
+struct SchemaIndex<'a> {
+    settings: HashMap<&'a str, &'a SettingSchema>,
+    categories: HashSet<&'a str>,
+}
+
+impl<'a> SchemaIndex<'a> {
+    fn new(categories: &'a [SettingCategory]) -> Self {
+        let mut index = Self {
+            settings: HashMap::new(),
+            categories: HashSet::new(),
+        };
+        index.add_categories(categories);
+        index
+    }
+
+    fn add_categories(&mut self, categories: &'a [SettingCategory]) {
+        for category in categories {
+            // The General category holds the top-level settings
+            if !category.path.is_empty() {
+                self.categories.insert(&category.path);
+            }
+            for setting in &category.settings {
+                self.settings.insert(&setting.path, setting);
+            }
+            self.add_categories(&category.subcategories);
+        }
+    }
+
+    fn walk(
+        &self,
+        value: &Value,
+        prefix: &str,
+        handled: &HashSet<String>,
+        result: &mut Reconciliation,
+    ) {
+        let Value::Object(map) = value else {
+            return;
+        };
+        for (key, child) in map {
+            let path = format!("{}/{}", prefix, key);
+            if handled.contains(&path) {
+                continue;
+            }
+            if let Some(setting) = self.settings.get(path.as_str()) {
+                if !value_matches(&setting.setting_type, child) {
+                    result.mismatched.push(MismatchedSetting {
+                        path,
+                        value: child.clone(),
+                        expected: expected_type_name(&setting.setting_type),
+                    });
+                }
+            } else if self.categories.contains(path.as_str()) {
+                if child.is_object() {
+                    self.walk(child, &path, handled, result);
+                } else if !child.is_null() {
+                    result.mismatched.push(MismatchedSetting {
+                        path,
+                        value: child.clone(),
+                        expected: "object",
+                    });
+                }
+            } else {
+                result.unrecognized.push(UnrecognizedSetting {
+                    path,
+                    value: child.clone(),
+                    keep: true,
+                });
+            }
+        }
+    }
+}
+
+/// Set the value at a JSON pointer, creating objects along the way
+fn insert_at_pointer(root: &mut Value, path: &str, value: Value) {
+    let mut keys = path.trim_start_matches('/').split('/').peekable();
+    let mut node = root;
+    while let Some(key) = keys.next() {
+        let Value::Object(map) = node else {
+            return;
+        };
+        if keys.peek().is_none() {
+            map.insert(key.to_string(), value);
+            return;
+        }
+        node = map
+            .entry(key.to_string())
+            .or_insert_with(|| Value::Object(Default::default()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::view::settings::schema::parse_schema;
+    use serde_json::json;
+
+    fn categories() -> Vec<SettingCategory> {
+        parse_schema(include_str!("../../../plugins/config-schema.json")).unwrap()
+    }
+
+    #[test]
+    fn test_renamed_retyped_and_unknown_keys() {
+        let user_config = json!({
+            "version": 1,
+            "editor": { "tabSize": 2, "line_wrap": false },
+            "files": { "auto_revert": false },
+            "legacy_option": { "enabled": true }
+        });
+
+        let result = reconcile(&categories(), &user_config);
+
+        assert_eq!(
+            result.migrated,
+            vec![
+                MigratedSetting {
+                    from: "/editor/tabSize".to_string(),
+                    path: "/editor/tab_size".to_string(),
+                    value: json!(2),
+                },
+                MigratedSetting {
+                    from: "/files/auto_revert".to_string(),
+                    path: "/files/auto_revert".to_string(),
+                    value: json!("off"),
+                },
+            ]
+        );
+        assert_eq!(
+            result.unrecognized,
+            vec![UnrecognizedSetting {
+                path: "/legacy_option".to_string(),
+                value: json!({ "enabled": true }),
+                keep: true,
+            }]
+        );
+        assert!(result.mismatched.is_empty());
+    }
+
+    #[test]
+    fn test_current_config_needs_nothing() {
+        let user_config = json!({
+            "theme": "dark",
+            "editor": { "tab_size": 2 },
+            "files": { "auto_revert": "ask" },
+            "languages": { "mylang": { "extensions": ["ml"] } }
+        });
+
+        assert!(reconcile(&categories(), &user_config).is_empty());
+    }
+
+    #[test]
+    fn test_rename_skipped_when_new_key_is_set() {
+        let user_config = json!({ "editor": { "tabSize": 2, "tab_size": 8 } });
+
+        let result = reconcile(&categories(), &user_config);
+
+        assert!(result.migrated.is_empty());
+        assert_eq!(result.unrecognized.len(), 1);
+        assert_eq!(result.unrecognized[0].path, "/editor/tabSize");
+    }
+
+    #[test]
+    fn test_type_mismatches() {
+        let user_config = json!({
+            "editor": { "line_wrap": "yes", "tab_size": 2.5 },
+            "terminal": 3
+        });
+
+        let result = reconcile(&categories(), &user_config);
+
+        let mut mismatched: Vec<_> = result
+            .mismatched
+            .iter()
+            .map(|s| (s.path.as_str(), s.expected))
+            .collect();
+        mismatched.sort();
+        assert_eq!(
+            mismatched,
+            vec![
+                ("/editor/line_wrap", "boolean"),
+                ("/editor/tab_size", "integer"),
+                ("/terminal", "object"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_preserved_values() {
+        let mut result = Reconciliation {
+            unrecognized: vec![
+                UnrecognizedSetting {
+                    path: "/editor/old".to_string(),
+                    value: json!(1),
+                    keep: true,
+                },
+                UnrecognizedSetting {
+                    path: "/gone".to_string(),
+                    value: json!(2),
+                    keep: false,
+                },
+            ],
+            mismatched: vec![MismatchedSetting {
+                path: "/editor/line_wrap".to_string(),
+                value: json!("yes"),
+                expected: "boolean",
+            }],
+            ..Default::default()
+        };
+
+        assert_eq!(
+            result.preserved_values(),
+            json!({ "editor": { "old": 1, "line_wrap": "yes" } })
+        );
+
+        result.unrecognized[0].keep = false;
+        assert_eq!(
+            result.preserved_values(),
+            json!({ "editor": { "line_wrap": "yes" } })
+        );
+    }
+}
@@ -8,11 +8,13 @@ use super::items::SettingControl;
 use super::layout::{SettingsHit, SettingsLayout};
 use super::search::SearchResult;
 use super::state::SettingsState;
+use crate::primitives::display_width::{byte_offset_at_visual_column, str_width};
 use crate::view::controls::{
     render_dropdown_aligned, render_number_input_aligned, render_text_input_aligned,
     render_toggle_aligned, DropdownColors, MapColors, NumberInputColors, TextInputColors,
     TextListColors, ToggleColors,
 };
+use crate::view::glyphs::glyphs;
 use crate::view::theme::Theme;
 use ratatui::layout::{Constraint, Layout, Rect};
 use ratatui::style::{Color, Modifier, Style};
@@ -121,7 +123,11 @@ pub fn render_settings(
     frame.render_widget(Clear, modal_area);
 
     let title = if state.has_changes() {
-        format!(" Settings [{}] • (modified) ", state.target_layer_name())
+        format!(
+            " Settings [{}] {} (modified) ",
+            state.target_layer_name(),
+            glyphs().bullet
+        )
     } else {
         format!(" Settings [{}] ", state.target_layer_name())
     };
@@ -129,6 +135,7 @@ pub fn render_settings(
     let block = Block::default()
         .title(title.as_str())
         .borders(Borders::ALL)
+        .border_set(glyphs().border_set())
         .border_style(Style::default().fg(theme.popup_border_fg))
         .style(Style::default().bg(theme.popup_bg));
     frame.render_widget(block, modal_area);
@@ -275,7 +282,7 @@ fn render_categories(
 
         // Indicator for categories with modified settings
         let has_changes = page.items.iter().any(|i| i.modified);
-        let modified_indicator = if has_changes { "●" } else { " " };
+        let modified_indicator = if has_changes { glyphs().modified } else { " " };
 
         // Show ">" when selected and focused for clearer selection indicator
         let selection_indicator = if is_selected && state.focus_panel == FocusPanel::Categories {
@@ -297,7 +304,8 @@ fn render_categories(
 fn render_separator(frame: &mut Frame, area: Rect, theme: &Theme) {
     for y in 0..area.height {
         let cell = Rect::new(area.x, area.y + y, 1, 1);
-        let sep = Paragraph::new("│").style(Style::default().fg(theme.split_separator_fg));
+        let sep = Paragraph::new(glyphs().border_vertical)
+            .style(Style::default().fg(theme.split_separator_fg));
         frame.render_widget(sep, cell);
     }
 }
@@ -601,10 +609,11 @@ fn render_setting_item_pure(
             } else {
                 // Single line with optional layer indicator
                 let mut display_desc = if description.len() > max_width.saturating_sub(12) {
-                    format!(
-                        "{}...",
-                        &description[..max_width.saturating_sub(15).max(10)]
-                    )
+                    let end = byte_offset_at_visual_column(
+                        description,
+                        max_width.saturating_sub(15).max(10),
+                    );
+                    format!("{}...", &description[..end])
                 } else {
                     description.clone()
                 };
@@ -743,7 +752,11 @@ fn render_control(
             // Render label with modified indicator
             let label_style = Style::default().fg(theme.editor_fg);
             let value_style = Style::default().fg(theme.line_number_fg);
-            let modified_indicator = if modified { "• " } else { "" };
+            let modified_indicator = if modified {
+                format!("{} ", glyphs().bullet)
+            } else {
+                String::new()
+            };
 
             let label = Span::styled(format!("{}{}: ", modified_indicator, name), label_style);
             let value = Span::styled(
@@ -754,6 +767,28 @@ fn render_control(
             frame.render_widget(Paragraph::new(Line::from(vec![label, value])), area);
             ControlLayoutInfo::Complex
         }
+
+        SettingControl::ReadOnly { value, reason } => {
+            let mut y = area.y;
+            if skip_rows == 0 {
+                let label =
+                    Span::styled(format!("{}: ", name), Style::default().fg(theme.editor_fg));
+                let value = Span::styled(value.as_str(), Style::default().fg(theme.line_number_fg));
+                frame.render_widget(
+                    Paragraph::new(Line::from(vec![label, value])),
+                    Rect::new(area.x, y, area.width, 1),
+                );
+                y += 1;
+            }
+            if skip_rows <= 1 && y < area.y + area.height {
+                frame.render_widget(
+                    Paragraph::new(format!("  {}", reason))
+                        .style(Style::default().fg(theme.diagnostic_warning_fg)),
+                    Rect::new(area.x, y, area.width, 1),
+                );
+            }
+            ControlLayoutInfo::Complex
+        }
     }
 }
 
@@ -800,7 +835,11 @@ fn render_json_control(
 
     // Row 0: label
     if content_row >= skip_rows {
-        let modified_indicator = if modified { "• " } else { "" };
+        let modified_indicator = if modified {
+            format!("{} ", glyphs().bullet)
+        } else {
+            String::new()
+        };
         let label_line = Line::from(vec![Span::styled(
             format!("{}{}:", modified_indicator, name),
             Style::default().fg(label_color),
@@ -873,10 +912,13 @@ fn render_json_control(
         // Build line with border
         let mut spans = vec![
             Span::raw(" ".repeat(indent as usize)),
-            Span::styled("│", Style::default().fg(border_color)),
+            Span::styled(glyphs().border_vertical, Style::default().fg(border_color)),
         ];
         spans.extend(content_spans);
-        spans.push(Span::styled("│", Style::default().fg(border_color)));
+        spans.push(Span::styled(
+            glyphs().border_vertical,
+            Style::default().fg(border_color),
+        ));
         let line = Line::from(spans);
 
         frame.render_widget(Paragraph::new(line), Rect::new(area.x, y, area.width, 1));
@@ -906,7 +948,7 @@ fn render_json_control(
     // Show invalid JSON indicator
     if !is_valid && y < area.y + area.height {
         let warning = Span::styled(
-            "  ⚠ Invalid JSON",
+            format!("  {} Invalid JSON", glyphs().warning),
             Style::default().fg(theme.diagnostic_warning_fg),
         );
         frame.render_widget(
@@ -1172,13 +1214,7 @@ fn render_map_partial(
         };
 
         // Get display value
-        let value_preview = state.get_display_value(value);
-        let max_preview_len = 20;
-        let value_preview = if value_preview.len() > max_preview_len {
-            format!("{}...", &value_preview[..max_preview_len - 3])
-        } else {
-            value_preview
-        };
+        let value_preview = state.display_preview(value, 20);
 
         let display_key: String = key.chars().take(key_width as usize).collect();
         let mut spans = vec![
@@ -1215,7 +1251,7 @@ fn render_map_partial(
         content_row += 1;
     }
 
-    // Add-new row (always show as button - dialog handles input)
+    // Add-new row: a button, or the key field while a key is being typed
     let add_row_area = if y < area.y + area.height && content_row >= skip_rows {
         let row_area = Rect::new(area.x, y, area.width, 1);
         let is_focused = state.focused_entry.is_none() && state.focus == FocusState::Focused;
@@ -1236,21 +1272,64 @@ fn render_map_partial(
             Style::default()
         };
 
-        let mut spans = vec![
-            Span::styled(" ".repeat(indent as usize), base_style),
-            Span::styled("[+] Add new", base_style.fg(colors.add_button)),
-        ];
-
-        if is_focused {
+        let mut spans = vec![Span::styled(" ".repeat(indent as usize), base_style)];
+
+        if is_focused && state.new_key.is_editing() {
+            // Typing a new key: show the field, scrolled to keep the cursor visible
+            let inner_width = key_width.saturating_sub(2) as usize;
+            let visible = state.new_key.visible(inner_width);
+            let text_style = base_style.fg(colors.label);
+            spans.push(Span::styled("[", base_style.fg(colors.border)));
+            let mut visible_width = 0;
+            for (text, selected) in visible.runs {
+                visible_width += str_width(&text);
+                let style = if selected {
+                    text_style.bg(colors.selection)
+                } else {
+                    text_style
+                };
+                spans.push(Span::styled(text, style));
+            }
             spans.push(Span::styled(
-                "  [Enter to add]",
-                base_style
-                    .fg(colors.value_preview)
-                    .add_modifier(Modifier::DIM),
+                " ".repeat(inner_width.saturating_sub(visible_width)),
+                base_style,
+            ));
+            spans.push(Span::styled("]", base_style.fg(colors.border)));
+            frame.render_widget(Paragraph::new(Line::from(spans)), row_area);
+
+            if visible.cursor_col < inner_width {
+                let cursor_x = area.x + indent + 1 + visible.cursor_col as u16;
+                let cursor_char = state.new_key.value[state.new_key.cursor..]
+                    .chars()
+                    .next()
+                    .unwrap_or(' ');
+                frame.render_widget(
+                    Paragraph::new(Span::styled(
+                        cursor_char.to_string(),
+                        Style::default()
+                            .fg(colors.cursor)
+                            .add_modifier(Modifier::REVERSED),
+                    )),
+                    Rect::new(cursor_x, y, 1, 1),
+                );
+            }
+        } else {
+            spans.push(Span::styled(
+                "[+] Add new",
+                base_style.fg(colors.add_button),
             ));
-        }
 
-        frame.render_widget(Paragraph::new(Line::from(spans)), row_area);
+            if is_focused {
+                spans.push(Span::styled(
+                    "  [Enter to add]",
+                    base_style
+                        .fg(colors.value_preview)
+                        .add_modifier(Modifier::DIM),
+                ));
+            }
+
+            frame.render_widget(Paragraph::new(Line::from(spans)), row_area);
+        }
         Some(row_area)
     } else {
         None
@@ -1296,7 +1375,11 @@ fn render_keybinding_list_partial(
 
     // Render label (row 0)
     if content_row >= skip_rows {
-        let modified_indicator = if modified { "• " } else { "" };
+        let modified_indicator = if modified {
+            format!("{} ", glyphs().bullet)
+        } else {
+            String::new()
+        };
         let label_line = Line::from(vec![Span::styled(
             format!("{}{}:", modified_indicator, state.label),
             Style::default().fg(colors.label_fg),
@@ -1345,9 +1428,15 @@ fn render_keybinding_list_partial(
                     format!("{:<20}", key_combo),
                     Style::default().fg(colors.key_fg).bg(bg),
                 ),
-                Span::styled(" → ", Style::default().fg(colors.label_fg).bg(bg)),
+                Span::styled(
+                    format!(" {} ", glyphs().maps_to),
+                    Style::default().fg(colors.label_fg).bg(bg),
+                ),
                 Span::styled(action, Style::default().fg(colors.action_fg).bg(bg)),
-                Span::styled(" [x]", Style::default().fg(colors.delete_fg).bg(bg)),
+                Span::styled(
+                    format!(" {}", glyphs().remove_button),
+                    Style::default().fg(colors.delete_fg).bg(bg),
+                ),
             ]);
             frame.render_widget(Paragraph::new(line), entry_area);
 
@@ -1440,7 +1529,7 @@ fn render_footer(
         modal_area.width.saturating_sub(2),
         1,
     );
-    let sep_line: String = "─".repeat(sep_area.width as usize);
+    let sep_line: String = glyphs().border_horizontal.repeat(sep_area.width as usize);
     frame.render_widget(
         Paragraph::new(sep_line).style(Style::default().fg(theme.split_separator_fg)),
         sep_area,
@@ -1601,12 +1690,19 @@ fn render_footer(
     // Help text (between Edit button and main buttons)
     let help_x = edit_x + edit_width + 2;
     let help_width = layer_x.saturating_sub(help_x + 1);
+    let glyphs = glyphs();
     let help = if state.search_active {
-        "Type to search, ↑↓:Navigate  Enter:Jump  Esc:Cancel"
+        format!(
+            "Type to search, {}{}:Navigate  Enter:Jump  Esc:Cancel",
+            glyphs.key_up, glyphs.key_down
+        )
     } else if footer_focused {
-        "Tab:Next button  Enter:Activate  Esc:Close"
+        "Tab:Next button  Enter:Activate  Esc:Close".to_string()
     } else {
-        "↑↓:Navigate  Tab:Next  Enter:Edit  /:Search  Esc:Close"
+        format!(
+            "{}{}:Navigate  Tab:Next  Enter:Edit  /:Search  Esc:Close",
+            glyphs.key_up, glyphs.key_down
+        )
     };
     let help_style = Style::default().fg(theme.line_number_fg);
     frame.render_widget(
@@ -1624,9 +1720,9 @@ fn render_search_header(frame: &mut Frame, area: Rect, state: &SettingsState, th
         .add_modifier(Modifier::UNDERLINED);
 
     let spans = vec![
-        Span::styled("🔍 ", search_style),
+        Span::styled(format!("{} ", glyphs().search), search_style),
         Span::styled(&state.search_query, search_style),
-        Span::styled("█", cursor_style), // Cursor
+        Span::styled(glyphs().text_cursor, cursor_style), // Cursor
     ];
     let line = Line::from(spans);
     frame.render_widget(
@@ -1822,6 +1918,7 @@ fn render_confirm_dialog(
     let block = Block::default()
         .title(title)
         .borders(Borders::ALL)
+        .border_set(glyphs().border_set())
         .border_style(Style::default().fg(theme.diagnostic_warning_fg))
         .style(Style::default().bg(theme.popup_bg));
     frame.render_widget(block, dialog_area);
@@ -1852,9 +1949,13 @@ fn render_confirm_dialog(
         .take((dialog_height as usize).saturating_sub(7))
     {
         let truncated = if change.len() > inner.width as usize - 2 {
-            format!("• {}...", &change[..inner.width as usize - 5])
+            format!(
+                "{} {}...",
+                glyphs().bullet,
+                &change[..inner.width as usize - 5]
+            )
         } else {
-            format!("• {}", change)
+            format!("{} {}", glyphs().bullet, change)
         };
         frame.render_widget(
             Paragraph::new(truncated).style(change_style),
@@ -1867,7 +1968,7 @@ fn render_confirm_dialog(
     let button_y = dialog_area.y + dialog_area.height - 3;
 
     // Draw separator
-    let sep_line: String = "─".repeat(inner.width as usize);
+    let sep_line: String = glyphs().border_horizontal.repeat(inner.width as usize);
     frame.render_widget(
         Paragraph::new(sep_line).style(Style::default().fg(theme.split_separator_fg)),
         Rect::new(inner.x, button_y - 1, inner.width, 1),
@@ -1909,7 +2010,11 @@ fn render_confirm_dialog(
     }
 
     // Help text
-    let help = "←/→: Select   Enter: Confirm   Esc: Cancel";
+    let help = format!(
+        "{}/{}: Select   Enter: Confirm   Esc: Cancel",
+        glyphs().key_left,
+        glyphs().key_right
+    );
     let help_style = Style::default().fg(theme.line_number_fg);
     frame.render_widget(
         Paragraph::new(help).style(help_style),
@@ -1947,6 +2052,7 @@ fn render_entry_dialog(
     let block = Block::default()
         .title(title)
         .borders(Borders::ALL)
+        .border_set(glyphs().border_set())
         .border_style(Style::default().fg(theme.popup_border_fg))
         .style(Style::default().bg(theme.popup_bg));
     frame.render_widget(block, dialog_area);
@@ -2140,21 +2246,32 @@ fn render_entry_dialog(
 
     if has_invalid_json && !is_json_control {
         // Text control with JSON validation - must fix before leaving
-        let warning = "⚠ Invalid JSON - fix before leaving field";
+        let warning = format!(
+            "{} Invalid JSON - fix before leaving field",
+            glyphs().warning
+        );
         let warning_style = Style::default().fg(theme.diagnostic_warning_fg);
         frame.render_widget(Paragraph::new(warning).style(warning_style), help_area);
     } else if has_invalid_json && is_json_control {
         // JSON control with invalid JSON
-        let warning = "⚠ Invalid JSON";
+        let warning = format!("{} Invalid JSON", glyphs().warning);
         let warning_style = Style::default().fg(theme.diagnostic_warning_fg);
         frame.render_widget(Paragraph::new(warning).style(warning_style), help_area);
     } else if is_json_control {
         // Editing JSON control
-        let help = "↑↓←→:Move  Enter:Newline  Tab/Esc:Exit";
+        let glyphs = glyphs();
+        let help = format!(
+            "{}{}{}{}:Move  Enter:Newline  Tab/Esc:Exit",
+            glyphs.key_up, glyphs.key_down, glyphs.key_left, glyphs.key_right
+        );
         let help_style = Style::default().fg(theme.line_number_fg);
         frame.render_widget(Paragraph::new(help).style(help_style), help_area);
     } else {
-        let help = "↑↓:Navigate  Tab:Fields/Buttons  Enter:Edit/Confirm  Esc:Cancel";
+        let help = format!(
+            "{}{}:Navigate  Tab:Fields/Buttons  Enter:Edit/Confirm  Esc:Cancel",
+            glyphs().key_up,
+            glyphs().key_down
+        );
         let help_style = Style::default().fg(theme.line_number_fg);
         frame.render_widget(Paragraph::new(help).style(help_style), help_area);
     }
@@ -2162,12 +2279,13 @@ fn render_entry_dialog(
 
 /// Render the help overlay showing keyboard shortcuts
 fn render_help_overlay(frame: &mut Frame, parent_area: Rect, theme: &Theme) {
+    let up_down = format!("{} / {}", glyphs().key_up, glyphs().key_down);
     // Define the help content
     let help_items = [
         (
             "Navigation",
             vec![
-                ("↑ / ↓", "Move up/down"),
+                (up_down.as_str(), "Move up/down"),
                 ("Tab", "Switch between categories and settings"),
                 ("Enter", "Activate/toggle setting"),
             ],
@@ -2177,7 +2295,7 @@ fn render_help_overlay(frame: &mut Frame, parent_area: Rect, theme: &Theme) {
             vec![
                 ("/", "Start search"),
                 ("Esc", "Cancel search"),
-                ("↑ / ↓", "Navigate results"),
+                (up_down.as_str(), "Navigate results"),
                 ("Enter", "Jump to result"),
             ],
         ),
@@ -2206,6 +2324,7 @@ fn render_help_overlay(frame: &mut Frame, parent_area: Rect, theme: &Theme) {
     let block = Block::default()
         .title(" Keyboard Shortcuts ")
         .borders(Borders::ALL)
+        .border_set(glyphs().border_set())
         .border_style(Style::default().fg(theme.menu_highlight_fg))
         .style(Style::default().bg(theme.popup_bg));
     frame.render_widget(block, dialog_area);
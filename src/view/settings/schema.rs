@@ -152,6 +152,8 @@ struct RawSchema {
     defs: Option<HashMap<String, RawSchema>>,
     #[serde(rename = "additionalProperties")]
     additional_properties: Option<AdditionalProperties>,
+    #[serde(rename = "anyOf")]
+    any_of: Option<Vec<RawSchema>>,
     /// Extensible enum values - see module docs for details
     #[serde(rename = "x-enum-values", default)]
     extensible_enum_values: Vec<EnumValueEntry>,
@@ -430,7 +432,16 @@ fn determine_type(
             if let Some(ref add_props) = resolved.additional_properties {
                 match add_props {
                     AdditionalProperties::Schema(schema_box) => {
-                        let inner_resolved = resolve_ref(schema_box, defs);
+                        // Values that can take several shapes (e.g. one LSP
+                        // server or a list) are edited in their first shape;
+                        // the others are shown read-only
+                        let inner = resolve_ref(schema_box, defs);
+                        let inner_resolved = match inner.any_of.as_deref() {
+                            Some([first, ..]) if inner.schema_type.is_none() => {
+                                resolve_ref(first, defs)
+                            }
+                            _ => inner,
+                        };
                         let value_schema =
                             parse_setting("value", "", inner_resolved, defs, enum_values_map);
 
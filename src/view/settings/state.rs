@@ -6,14 +6,21 @@
 use super::entry_dialog::EntryDialogState;
 use super::items::{control_to_value, SettingControl, SettingItem, SettingsPage};
 use super::layout::SettingsHit;
+use super::reconcile::{reconcile, Reconciliation, UnrecognizedSetting};
 use super::schema::{parse_schema, SettingCategory, SettingSchema};
 use super::search::{search_settings, SearchResult};
 use crate::config::Config;
 use crate::config_io::ConfigLayer;
-use crate::view::controls::FocusState;
-use crate::view::ui::ScrollablePanel;
+use crate::view::controls::{DropdownState, FocusState};
+use crate::view::ui::{LineEdit, ScrollablePanel};
+use rust_i18n::t;
 use std::collections::HashMap;
 
+/// Dropdown value that keeps an unrecognized setting
+const KEEP_UNRECOGNIZED: &str = "keep";
+/// Dropdown value that deletes an unrecognized setting
+const DELETE_UNRECOGNIZED: &str = "delete";
+
 /// Info needed to open a nested dialog (extracted before mutable borrow)
 enum NestedDialogInfo {
     MapEntry {
@@ -100,6 +107,9 @@ pub struct SettingsState {
     /// Maps JSON pointer paths (e.g., "/editor/tab_size") to their source layer.
     /// Values not in this map come from system defaults.
     pub layer_sources: HashMap<String, ConfigLayer>,
+    /// Differences between the user config file and the schema
+    /// (see [`Self::reconcile_user_config`])
+    pub reconciliation: Reconciliation,
 }
 
 impl SettingsState {
@@ -134,9 +144,72 @@ impl SettingsState {
             entry_dialog_stack: Vec::new(),
             target_layer: ConfigLayer::User, // Default to user-global settings
             layer_sources: HashMap::new(),   // Populated via set_layer_sources()
+            reconciliation: Reconciliation::default(),
         })
     }
 
+    /// Check the user config file (as written) against the schema.
+    ///
+    /// Migrated settings become pending changes, so they are written back on
+    /// the next save; values of the wrong type are shown read-only; unknown keys
+    /// are listed on an "Unrecognized Settings" page, where they can be kept or
+    /// deleted.
+    pub fn reconcile_user_config(&mut self, user_config: &serde_json::Value) {
+        self.reconciliation = reconcile(&self.categories, user_config);
+        if !self.reconciliation.is_empty() {
+            self.rebuild_pages();
+        }
+    }
+
+    /// Stored values the typed config can't hold, to be written back to the user
+    /// config as they are
+    pub fn preserved_values(&self) -> serde_json::Value {
+        self.reconciliation.preserved_values()
+    }
+
+    /// Build the pages from the original config and the reconciliation result
+    fn rebuild_pages(&mut self) {
+        self.pages = super::items::build_pages(&self.categories, &self.original_config);
+
+        for migrated in self.reconciliation.migrated.clone() {
+            // Flagged even if unchanged, so the old key is dropped on save
+            self.pending_changes
+                .insert(migrated.path.clone(), migrated.value.clone());
+            if let Some(item) = self.find_item_mut(&migrated.path) {
+                update_control_from_value(&mut item.control, &migrated.value);
+                item.modified = item.default.as_ref() != Some(&migrated.value);
+            }
+        }
+
+        for mismatched in self.reconciliation.mismatched.clone() {
+            if let Some(item) = self.find_item_mut(&mismatched.path) {
+                item.control = SettingControl::ReadOnly {
+                    value: mismatched.value.to_string(),
+                    reason: t!(
+                        "settings.value_type_mismatch",
+                        expected = mismatched.expected
+                    )
+                    .to_string(),
+                };
+                item.default = None;
+                item.modified = true;
+            }
+        }
+
+        if !self.reconciliation.unrecognized.is_empty() {
+            self.pages
+                .push(unrecognized_page(&self.reconciliation.unrecognized));
+        }
+    }
+
+    /// Find the item for a setting path on any page
+    fn find_item_mut(&mut self, path: &str) -> Option<&mut SettingItem> {
+        self.pages
+            .iter_mut()
+            .flat_map(|page| page.items.iter_mut())
+            .find(|item| item.path == path)
+    }
+
     /// Show the settings panel
     pub fn show(&mut self) {
         self.visible = true;
@@ -360,7 +433,7 @@ impl SettingsState {
 
     /// Check if there are unsaved changes
     pub fn has_changes(&self) -> bool {
-        !self.pending_changes.is_empty()
+        !self.pending_changes.is_empty() || self.reconciliation.unrecognized.iter().any(|s| !s.keep)
     }
 
     /// Apply pending changes to a config
@@ -379,8 +452,11 @@ impl SettingsState {
     /// Discard all pending changes
     pub fn discard_changes(&mut self) {
         self.pending_changes.clear();
+        for setting in &mut self.reconciliation.unrecognized {
+            setting.keep = true;
+        }
         // Rebuild pages from original config
-        self.pages = super::items::build_pages(&self.categories, &self.original_config);
+        self.rebuild_pages();
     }
 
     /// Set the target layer for saving changes.
@@ -472,6 +548,15 @@ impl SettingsState {
         });
 
         if let Some((path, value, modified)) = change_info {
+            if let Some(setting) = self
+                .reconciliation
+                .unrecognized
+                .iter_mut()
+                .find(|s| s.path == path)
+            {
+                setting.keep = value != DELETE_UNRECOGNIZED;
+                return;
+            }
             // Update modified flag
             if let Some(item) = self.current_item_mut() {
                 item.modified = modified;
@@ -503,7 +588,7 @@ impl SettingsState {
                     SettingControl::Map(state) => state.focus = focus,
                     SettingControl::ObjectArray(state) => state.focus = focus,
                     SettingControl::Json(state) => state.focus = focus,
-                    SettingControl::Complex { .. } => {}
+                    SettingControl::Complex { .. } | SettingControl::ReadOnly { .. } => {}
                 }
             }
         }
@@ -646,6 +731,9 @@ impl SettingsState {
         let Some((key, value)) = map_state.entries.get(entry_idx) else {
             return;
         };
+        if !map_state.entry_matches_schema(value) {
+            return;
+        }
 
         // Get the value schema for this map
         let Some(schema) = map_state.value_schema.as_ref() else {
@@ -744,6 +832,9 @@ impl SettingsState {
                     if let Some(entry_idx) = map_state.focused_entry {
                         // Edit existing entry
                         let (key, value) = map_state.entries.get(entry_idx)?;
+                        if !map_state.entry_matches_schema(value) {
+                            return None;
+                        }
                         Some(NestedDialogInfo::MapEntry {
                             key: key.clone(),
                             value: value.clone(),
@@ -1025,12 +1116,20 @@ impl SettingsState {
                 SettingControl::TextList(_) | SettingControl::Text(_) | SettingControl::Map(_)
             ) {
                 self.editing_text = true;
+                // Remember the value so Ctrl+Z can revert to it
+                if let Some(edit) = self.text_line_edit_mut() {
+                    edit.begin_edit();
+                }
             }
         }
     }
 
     /// Stop text editing mode
     pub fn stop_editing(&mut self) {
+        if let Some(edit) = self.text_line_edit_mut() {
+            edit.clear_selection();
+            edit.end_edit();
+        }
         self.editing_text = false;
     }
 
@@ -1044,19 +1143,34 @@ impl SettingsState {
         })
     }
 
+    /// Single-line edit state of the current Text control or Map add-new field
+    pub fn text_line_edit_mut(&mut self) -> Option<&mut LineEdit> {
+        match &mut self.current_item_mut()?.control {
+            SettingControl::Text(state) => Some(&mut state.edit),
+            SettingControl::Map(state) if state.focused_entry.is_none() => Some(&mut state.new_key),
+            _ => None,
+        }
+    }
+
     /// Insert a character into the current editable control
     pub fn text_insert(&mut self, c: char) {
         if let Some(item) = self.current_item_mut() {
             match &mut item.control {
                 SettingControl::TextList(state) => state.insert(c),
-                SettingControl::Text(state) => {
-                    state.value.insert(state.cursor, c);
-                    state.cursor += c.len_utf8();
-                }
-                SettingControl::Map(state) => {
-                    state.new_key_text.insert(state.cursor, c);
-                    state.cursor += c.len_utf8();
-                }
+                SettingControl::Text(state) => state.insert(c),
+                SettingControl::Map(state) => state.insert(c),
+                _ => {}
+            }
+        }
+    }
+
+    /// Insert pasted text into the current editable control
+    pub fn text_insert_str(&mut self, s: &str) {
+        if let Some(item) = self.current_item_mut() {
+            match &mut item.control {
+                SettingControl::TextList(state) => state.insert_str(s),
+                SettingControl::Text(state) => state.insert_str(s),
+                SettingControl::Map(state) => state.insert_str(s),
                 _ => {}
             }
         }
@@ -1067,26 +1181,8 @@ impl SettingsState {
         if let Some(item) = self.current_item_mut() {
             match &mut item.control {
                 SettingControl::TextList(state) => state.backspace(),
-                SettingControl::Text(state) => {
-                    if state.cursor > 0 {
-                        let mut char_start = state.cursor - 1;
-                        while char_start > 0 && !state.value.is_char_boundary(char_start) {
-                            char_start -= 1;
-                        }
-                        state.value.remove(char_start);
-                        state.cursor = char_start;
-                    }
-                }
-                SettingControl::Map(state) => {
-                    if state.cursor > 0 {
-                        let mut char_start = state.cursor - 1;
-                        while char_start > 0 && !state.new_key_text.is_char_boundary(char_start) {
-                            char_start -= 1;
-                        }
-                        state.new_key_text.remove(char_start);
-                        state.cursor = char_start;
-                    }
-                }
+                SettingControl::Text(state) => state.backspace(),
+                SettingControl::Map(state) => state.backspace(),
                 _ => {}
             }
         }
@@ -1097,24 +1193,8 @@ impl SettingsState {
         if let Some(item) = self.current_item_mut() {
             match &mut item.control {
                 SettingControl::TextList(state) => state.move_left(),
-                SettingControl::Text(state) => {
-                    if state.cursor > 0 {
-                        let mut new_pos = state.cursor - 1;
-                        while new_pos > 0 && !state.value.is_char_boundary(new_pos) {
-                            new_pos -= 1;
-                        }
-                        state.cursor = new_pos;
-                    }
-                }
-                SettingControl::Map(state) => {
-                    if state.cursor > 0 {
-                        let mut new_pos = state.cursor - 1;
-                        while new_pos > 0 && !state.new_key_text.is_char_boundary(new_pos) {
-                            new_pos -= 1;
-                        }
-                        state.cursor = new_pos;
-                    }
-                }
+                SettingControl::Text(state) => state.move_left(),
+                SettingControl::Map(state) => state.move_left(),
                 _ => {}
             }
         }
@@ -1125,27 +1205,8 @@ impl SettingsState {
         if let Some(item) = self.current_item_mut() {
             match &mut item.control {
                 SettingControl::TextList(state) => state.move_right(),
-                SettingControl::Text(state) => {
-                    if state.cursor < state.value.len() {
-                        let mut new_pos = state.cursor + 1;
-                        while new_pos < state.value.len() && !state.value.is_char_boundary(new_pos)
-                        {
-                            new_pos += 1;
-                        }
-                        state.cursor = new_pos;
-                    }
-                }
-                SettingControl::Map(state) => {
-                    if state.cursor < state.new_key_text.len() {
-                        let mut new_pos = state.cursor + 1;
-                        while new_pos < state.new_key_text.len()
-                            && !state.new_key_text.is_char_boundary(new_pos)
-                        {
-                            new_pos += 1;
-                        }
-                        state.cursor = new_pos;
-                    }
-                }
+                SettingControl::Text(state) => state.move_right(),
+                SettingControl::Map(state) => state.move_right(),
                 _ => {}
             }
         }
@@ -1524,6 +1585,12 @@ impl SettingsState {
 
     /// Get list of pending changes for display
     pub fn get_change_descriptions(&self) -> Vec<String> {
+        let deleted = self
+            .reconciliation
+            .unrecognized
+            .iter()
+            .filter(|s| !s.keep)
+            .map(|s| format!("{}: {}", s.path, t!("settings.unrecognized_delete")));
         self.pending_changes
             .iter()
             .map(|(path, value)| {
@@ -1535,10 +1602,50 @@ impl SettingsState {
                 };
                 format!("{}: {}", path, value_str)
             })
+            .chain(deleted)
             .collect()
     }
 }
 
+/// Page listing the keys of the user config the schema doesn't know, each with
+/// a Keep/Delete choice and its stored value as the description
+fn unrecognized_page(settings: &[UnrecognizedSetting]) -> SettingsPage {
+    let items = settings
+        .iter()
+        .map(|setting| {
+            let name = setting.path.trim_start_matches('/').replace('/', ".");
+            let control = DropdownState::with_values(
+                vec![
+                    t!("settings.unrecognized_keep").to_string(),
+                    t!("settings.unrecognized_delete").to_string(),
+                ],
+                vec![
+                    KEEP_UNRECOGNIZED.to_string(),
+                    DELETE_UNRECOGNIZED.to_string(),
+                ],
+                &name,
+            )
+            .with_selected(if setting.keep { 0 } else { 1 });
+            SettingItem {
+                path: setting.path.clone(),
+                name,
+                description: Some(setting.value.to_string()),
+                control: SettingControl::Dropdown(control),
+                default: None,
+                modified: false,
+            }
+        })
+        .collect();
+
+    SettingsPage {
+        name: t!("settings.unrecognized_title").to_string(),
+        path: String::new(),
+        description: Some(t!("settings.unrecognized_description").to_string()),
+        items,
+        subpages: Vec::new(),
+    }
+}
+
 /// Update a control's state from a JSON value
 fn update_control_from_value(control: &mut SettingControl, value: &serde_json::Value) {
     match control {
@@ -1561,8 +1668,7 @@ fn update_control_from_value(control: &mut SettingControl, value: &serde_json::V
         }
         SettingControl::Text(state) => {
             if let Some(s) = value.as_str() {
-                state.value = s.to_string();
-                state.cursor = state.value.len();
+                state.edit.set_value(s);
             }
         }
         SettingControl::TextList(state) => {
@@ -1597,7 +1703,7 @@ fn update_control_from_value(control: &mut SettingControl, value: &serde_json::V
             state.editor.set_value(&json_str);
             state.scroll_offset = 0;
         }
-        SettingControl::Complex { .. } => {}
+        SettingControl::Complex { .. } | SettingControl::ReadOnly { .. } => {}
     }
 }
 
@@ -1947,4 +2053,82 @@ mod tests {
         state.cycle_target_layer();
         assert!(!state.has_changes());
     }
+
+    #[test]
+    fn test_reconcile_user_config() {
+        let schema = include_str!("../../../plugins/config-schema.json");
+        let mut state = SettingsState::new(schema, &test_config()).unwrap();
+        state.reconcile_user_config(&serde_json::json!({
+            "editor": { "tabSize": 2, "line_wrap": "yes" },
+            "files": { "auto_revert": false },
+            "legacy_option": { "enabled": true }
+        }));
+
+        // Migrated values are pending changes shown in their controls
+        assert_eq!(
+            state.pending_changes.get("/editor/tab_size"),
+            Some(&serde_json::json!(2))
+        );
+        assert_eq!(
+            state.pending_changes.get("/files/auto_revert"),
+            Some(&serde_json::json!("off"))
+        );
+        let item = |state: &SettingsState, path: &str| {
+            state
+                .pages
+                .iter()
+                .flat_map(|page| page.items.iter())
+                .find(|item| item.path == path)
+                .cloned()
+                .unwrap()
+        };
+        match item(&state, "/editor/tab_size").control {
+            SettingControl::Number(n) => assert_eq!(n.value, 2),
+            other => panic!("Expected number control, got {:?}", other),
+        }
+
+        // The mismatched value is shown read-only
+        match item(&state, "/editor/line_wrap").control {
+            SettingControl::ReadOnly { value, reason } => {
+                assert_eq!(value, "\"yes\"");
+                assert!(reason.contains("boolean"));
+            }
+            other => panic!("Expected read-only control, got {:?}", other),
+        }
+
+        // The unknown key is on the last page, kept by default
+        let page = state.pages.last().unwrap();
+        assert_eq!(page.name, t!("settings.unrecognized_title"));
+        assert_eq!(page.items.len(), 1);
+        assert_eq!(page.items[0].name, "legacy_option");
+        assert_eq!(
+            state.preserved_values(),
+            serde_json::json!({
+                "editor": { "line_wrap": "yes" },
+                "legacy_option": { "enabled": true }
+            })
+        );
+
+        let config = state.apply_changes(&test_config()).unwrap();
+        assert_eq!(config.editor.tab_size, 2);
+        assert_eq!(config.files.auto_revert, crate::config::AutoRevertMode::Off);
+
+        // Choosing Delete drops it from the preserved values
+        state.selected_category = state.pages.len() - 1;
+        state.selected_item = 0;
+        state.focus_panel = FocusPanel::Settings;
+        state.dropdown_toggle();
+        state.dropdown_next();
+        state.dropdown_confirm();
+        assert!(!state.reconciliation.unrecognized[0].keep);
+        assert_eq!(
+            state.preserved_values(),
+            serde_json::json!({ "editor": { "line_wrap": "yes" } })
+        );
+
+        // Discarding restores the state the settings were opened with
+        state.discard_changes();
+        assert!(state.reconciliation.unrecognized[0].keep);
+        assert!(state.pending_changes.contains_key("/editor/tab_size"));
+    }
 }
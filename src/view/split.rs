@@ -25,11 +25,136 @@
 /// ```
 use crate::model::cursor::Cursors;
 use crate::model::event::{BufferId, SplitDirection, SplitId};
+use crate::services::cache_budget::{BudgetedCache, CachePriority};
+use crate::view::overlay::OverlayNamespace;
+use crate::view::ui::line_layout::LineLayoutCache;
 use crate::view::ui::view_pipeline::Layout;
+use crate::view::ui::winbar::WinbarSegment;
 use crate::view::viewport::Viewport;
 use crate::{services::plugins::api::ViewTransformPayload, state::ViewMode};
 use ratatui::layout::Rect;
 use serde::{Deserialize, Serialize};
+use std::ops::Range;
+
+/// Search state for find/replace functionality, kept per split so panes can
+/// search for different things
+#[derive(Debug, Clone)]
+pub struct SearchState {
+    /// The search query
+    pub query: String,
+    /// Buffer the matches were found in
+    pub buffer_id: BufferId,
+    /// All match positions in the buffer (byte offsets)
+    pub matches: Vec<usize>,
+    /// Index of the currently selected match
+    pub current_match_index: Option<usize>,
+    /// Whether search wraps around at document boundaries
+    pub wrap_search: bool,
+    /// Optional search range (for search in selection)
+    pub search_range: Option<Range<usize>>,
+    /// Options the search was made with
+    pub case_sensitive: bool,
+    pub whole_word: bool,
+    pub use_regex: bool,
+}
+
+const SEARCH_NAMESPACE_PREFIX: &str = "search:";
+
+/// Overlay namespace of the search highlights of a split
+///
+/// Highlights live on the buffer, which other splits may show too; each split
+/// only renders the search highlights in its own namespace.
+pub fn search_namespace(split_id: SplitId) -> OverlayNamespace {
+    OverlayNamespace::from_string(format!("{SEARCH_NAMESPACE_PREFIX}{}", split_id.0))
+}
+
+/// Whether a namespace holds the search highlights of some split
+pub fn is_search_namespace(namespace: &OverlayNamespace) -> bool {
+    namespace.as_str().starts_with(SEARCH_NAMESPACE_PREFIX)
+}
+
+/// Scroll position and line layouts of a buffer a split showed before
+#[derive(Debug, Clone)]
+struct RetainedView {
+    buffer_id: BufferId,
+    /// Buffer revision the view was kept at; any edit since makes it stale
+    revision: u64,
+    top_byte: usize,
+    top_view_line_offset: usize,
+    left_column: usize,
+    line_layout: LineLayoutCache,
+    bytes: usize,
+}
+
+/// Views of the buffers a split showed recently, so switching back to an
+/// unchanged buffer shows it where it was without laying it out again
+///
+/// The views share the editor's cache budget; when it's exceeded the least
+/// recently left ones are dropped first.
+#[derive(Debug, Clone, Default)]
+pub struct RetainedViews {
+    /// Least recently left first
+    views: Vec<RetainedView>,
+    bytes: usize,
+    /// Switches back to a buffer that restored its view, and ones that didn't
+    hits: u64,
+    misses: u64,
+}
+
+impl RetainedViews {
+    fn insert(&mut self, view: RetainedView) {
+        self.remove(view.buffer_id);
+        self.bytes += view.bytes;
+        self.views.push(view);
+    }
+
+    fn take(&mut self, buffer_id: BufferId) -> Option<RetainedView> {
+        let index = self.views.iter().position(|v| v.buffer_id == buffer_id)?;
+        let view = self.views.remove(index);
+        self.bytes -= view.bytes;
+        Some(view)
+    }
+
+    /// Forget the view of a buffer
+    pub fn remove(&mut self, buffer_id: BufferId) {
+        self.take(buffer_id);
+    }
+
+    /// Number of buffers with a kept view
+    pub fn len(&self) -> usize {
+        self.views.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.views.is_empty()
+    }
+}
+
+impl BudgetedCache for RetainedViews {
+    fn name(&self) -> &'static str {
+        "retained_views"
+    }
+
+    /// Only saves laying out a buffer again when switching back to it
+    fn priority(&self) -> CachePriority {
+        CachePriority::Low
+    }
+
+    fn approx_bytes(&self) -> usize {
+        self.bytes
+    }
+
+    fn hits_and_misses(&self) -> (u64, u64) {
+        (self.hits, self.misses)
+    }
+
+    fn shrink(&mut self, target_bytes: usize) {
+        while self.bytes > target_bytes && !self.views.is_empty() {
+            let evicted = self.views.remove(0);
+            self.bytes -= evicted.bytes;
+        }
+    }
+}
 
 /// A node in the split tree
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -109,6 +234,23 @@ pub struct SplitViewState {
     /// Sync group ID for synchronized scrolling
     /// Splits with the same sync_group will scroll together
     pub sync_group: Option<u32>,
+
+    /// Layouts of the source lines last rendered in this split, reused for
+    /// lines whose text has not changed
+    pub line_layout: LineLayoutCache,
+
+    /// Scroll positions and layouts of the buffers this split showed before
+    pub retained_views: RetainedViews,
+
+    /// Edits typed while this split has focus are refused ("Toggle Pane Lock")
+    pub input_locked: bool,
+
+    /// Active search of this split ("Find Next" and the match highlights
+    /// follow it)
+    pub search: Option<SearchState>,
+
+    /// Segments plugins set on this split's winbar
+    pub winbar_segments: Vec<WinbarSegment>,
 }
 
 impl SplitViewState {
@@ -128,6 +270,11 @@ impl SplitViewState {
             layout_dirty: true, // Start dirty so first operation builds layout
             previous_buffer: None,
             sync_group: None,
+            line_layout: LineLayoutCache::new(),
+            retained_views: RetainedViews::default(),
+            input_locked: false,
+            search: None,
+            winbar_segments: Vec::new(),
         }
     }
 
@@ -147,6 +294,11 @@ impl SplitViewState {
             layout_dirty: true, // Start dirty so first operation builds layout
             previous_buffer: None,
             sync_group: None,
+            line_layout: LineLayoutCache::new(),
+            retained_views: RetainedViews::default(),
+            input_locked: false,
+            search: None,
+            winbar_segments: Vec::new(),
         }
     }
 
@@ -194,6 +346,44 @@ impl SplitViewState {
     /// Remove a buffer from this split's tabs
     pub fn remove_buffer(&mut self, buffer_id: BufferId) {
         self.open_buffers.retain(|&id| id != buffer_id);
+        self.retained_views.remove(buffer_id);
+    }
+
+    /// Keep the scroll position and layouts of a buffer this split stops
+    /// showing, at the buffer's current `revision`
+    pub fn retain_view(&mut self, buffer_id: BufferId, revision: u64) {
+        let line_layout = std::mem::take(&mut self.line_layout);
+        let bytes = std::mem::size_of::<RetainedView>() + line_layout.approx_bytes();
+        self.retained_views.insert(RetainedView {
+            buffer_id,
+            revision,
+            top_byte: self.viewport.top_byte,
+            top_view_line_offset: self.viewport.top_view_line_offset,
+            left_column: self.viewport.left_column,
+            line_layout,
+            bytes,
+        });
+    }
+
+    /// Bring back the kept view of a buffer this split shows again, unless
+    /// the buffer changed since (its `revision` differs)
+    ///
+    /// Returns whether a view was restored.
+    pub fn restore_view(&mut self, buffer_id: BufferId, revision: u64) -> bool {
+        match self.retained_views.take(buffer_id) {
+            Some(view) if view.revision == revision => {
+                self.viewport.top_byte = view.top_byte;
+                self.viewport.top_view_line_offset = view.top_view_line_offset;
+                self.viewport.left_column = view.left_column;
+                self.line_layout = view.line_layout;
+                self.retained_views.hits += 1;
+                true
+            }
+            _ => {
+                self.retained_views.misses += 1;
+                false
+            }
+        }
     }
 
     /// Check if a buffer is open in this split
@@ -321,38 +511,60 @@ impl SplitNode {
                 split_id,
             } => {
                 let (first_rect, second_rect) = split_rect(rect, *direction, *ratio);
-                let mut separators = Vec::new();
+                let mut separators = vec![separator_line(*split_id, *direction, rect, first_rect)];
 
-                // Add separator for this split (in the 1-char gap between first and second)
-                match direction {
+                // Recursively get separators from children
+                separators.extend(first.get_separators_with_ids(first_rect));
+                separators.extend(second.get_separators_with_ids(second_rect));
+                separators
+            }
+        }
+    }
+
+    /// Lay out the leaves and separators that fit in `rect`
+    ///
+    /// Where `rect` is too small for both sides of a split, the side holding
+    /// `keep` (or else the first side) takes all of it and the other side is
+    /// hidden, so no leaf is narrower than [`MIN_SPLIT_WIDTH`] or shorter
+    /// than [`MIN_SPLIT_HEIGHT`] unless `rect` itself is.
+    fn layout_visible(
+        &self,
+        rect: Rect,
+        keep: SplitId,
+        leaves: &mut Vec<(SplitId, BufferId, Rect)>,
+        separators: &mut Vec<(SplitId, SplitDirection, u16, u16, u16)>,
+    ) {
+        match self {
+            SplitNode::Leaf {
+                buffer_id,
+                split_id,
+            } => leaves.push((*split_id, *buffer_id, rect)),
+            SplitNode::Split {
+                direction,
+                first,
+                second,
+                ratio,
+                split_id,
+            } => {
+                let (first_rect, second_rect) = split_rect(rect, *direction, *ratio);
+                let fits = match direction {
                     SplitDirection::Horizontal => {
-                        // Horizontal split: separator line is between first and second
-                        // y position is at the end of first rect (the gap line)
-                        separators.push((
-                            *split_id,
-                            SplitDirection::Horizontal,
-                            rect.x,
-                            first_rect.y + first_rect.height,
-                            rect.width,
-                        ));
+                        first_rect.height >= MIN_SPLIT_HEIGHT
+                            && second_rect.height >= MIN_SPLIT_HEIGHT
                     }
                     SplitDirection::Vertical => {
-                        // Vertical split: separator line is between first and second
-                        // x position is at the end of first rect (the gap column)
-                        separators.push((
-                            *split_id,
-                            SplitDirection::Vertical,
-                            first_rect.x + first_rect.width,
-                            rect.y,
-                            rect.height,
-                        ));
+                        first_rect.width >= MIN_SPLIT_WIDTH && second_rect.width >= MIN_SPLIT_WIDTH
                     }
+                };
+                if fits {
+                    separators.push(separator_line(*split_id, *direction, rect, first_rect));
+                    first.layout_visible(first_rect, keep, leaves, separators);
+                    second.layout_visible(second_rect, keep, leaves, separators);
+                } else if second.find(keep).is_some() {
+                    second.layout_visible(rect, keep, leaves, separators);
+                } else {
+                    first.layout_visible(rect, keep, leaves, separators);
                 }
-
-                // Recursively get separators from children
-                separators.extend(first.get_separators_with_ids(first_rect));
-                separators.extend(second.get_separators_with_ids(second_rect));
-                separators
             }
         }
     }
@@ -391,6 +603,43 @@ impl SplitNode {
     }
 }
 
+/// Narrowest a split is shown; a side of a split that would be narrower is
+/// hidden while there isn't room for it
+pub const MIN_SPLIT_WIDTH: u16 = 6;
+
+/// Shortest a split is shown: its tab bar and a line of text
+pub const MIN_SPLIT_HEIGHT: u16 = 2;
+
+/// The separator line of a split, in the 1-char gap between its two sides
+/// Returns (split_id, direction, x, y, length)
+fn separator_line(
+    split_id: SplitId,
+    direction: SplitDirection,
+    rect: Rect,
+    first_rect: Rect,
+) -> (SplitId, SplitDirection, u16, u16, u16) {
+    match direction {
+        // Horizontal split: separator line is between first and second
+        // y position is at the end of first rect (the gap line)
+        SplitDirection::Horizontal => (
+            split_id,
+            direction,
+            rect.x,
+            first_rect.y + first_rect.height,
+            rect.width,
+        ),
+        // Vertical split: separator line is between first and second
+        // x position is at the end of first rect (the gap column)
+        SplitDirection::Vertical => (
+            split_id,
+            direction,
+            first_rect.x + first_rect.width,
+            rect.y,
+            rect.height,
+        ),
+    }
+}
+
 /// Split a rectangle into two parts based on direction and ratio
 /// Leaves 1 character space for the separator line between splits
 fn split_rect(rect: Rect, direction: SplitDirection, ratio: f32) -> (Rect, Rect) {
@@ -442,6 +691,31 @@ fn split_rect(rect: Rect, direction: SplitDirection, ratio: f32) -> (Rect, Rect)
     }
 }
 
+/// A change to the split tree, kept until the editor turns it into a plugin
+/// hook, see [`SplitManager::take_changes`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SplitChange {
+    /// A split was divided: `split_id` is the new pane, `parent_id` the
+    /// container now holding it and the pane that was split
+    Created {
+        split_id: SplitId,
+        parent_id: SplitId,
+        direction: SplitDirection,
+    },
+    Closed {
+        split_id: SplitId,
+    },
+    Focused {
+        split_id: SplitId,
+        previous_split_id: SplitId,
+    },
+    /// The ratio of a split container changed
+    Resized {
+        split_id: SplitId,
+        ratio: f32,
+    },
+}
+
 /// Manager for the split view system
 #[derive(Debug)]
 pub struct SplitManager {
@@ -456,6 +730,9 @@ pub struct SplitManager {
 
     /// Currently maximized split (if any). When set, only this split is visible.
     maximized_split: Option<SplitId>,
+
+    /// Changes to the tree not yet taken by [`Self::take_changes`]
+    changes: Vec<SplitChange>,
 }
 
 impl SplitManager {
@@ -467,6 +744,23 @@ impl SplitManager {
             active_split: split_id,
             next_split_id: 1,
             maximized_split: None,
+            changes: Vec::new(),
+        }
+    }
+
+    /// Take the changes made to the tree since the last call, oldest first
+    pub fn take_changes(&mut self) -> Vec<SplitChange> {
+        std::mem::take(&mut self.changes)
+    }
+
+    /// Make `split_id` the active split, recording the change of focus
+    fn focus(&mut self, split_id: SplitId) {
+        if self.active_split != split_id {
+            self.changes.push(SplitChange::Focused {
+                split_id,
+                previous_split_id: self.active_split,
+            });
+            self.active_split = split_id;
         }
     }
 
@@ -484,7 +778,7 @@ impl SplitManager {
     pub fn set_active_split(&mut self, split_id: SplitId) -> bool {
         // Verify the split exists
         if self.root.find(split_id).is_some() {
-            self.active_split = split_id;
+            self.focus(split_id);
             true
         } else {
             false
@@ -532,6 +826,11 @@ impl SplitManager {
         Err(format!("Split {:?} not found", split_id))
     }
 
+    /// ID the next new split will get
+    pub fn next_split_id(&self) -> usize {
+        self.next_split_id
+    }
+
     /// Allocate a new split ID
     fn allocate_split_id(&mut self) -> SplitId {
         let id = SplitId(self.next_split_id);
@@ -553,7 +852,7 @@ impl SplitManager {
 
         if let Ok(new_split_id) = result {
             // Set the new split as active
-            self.active_split = new_split_id;
+            self.focus(new_split_id);
             Ok(new_split_id)
         } else {
             result
@@ -585,6 +884,11 @@ impl SplitManager {
                 ratio,
                 new_split_id,
             );
+            self.changes.push(SplitChange::Created {
+                split_id: new_leaf_id,
+                parent_id: new_split_id,
+                direction,
+            });
 
             return Ok(new_leaf_id);
         }
@@ -600,6 +904,11 @@ impl SplitManager {
                 ratio,
                 new_split_id,
             );
+            self.changes.push(SplitChange::Created {
+                split_id: new_leaf_id,
+                parent_id: new_split_id,
+                direction,
+            });
 
             Ok(new_leaf_id)
         } else {
@@ -627,12 +936,15 @@ impl SplitManager {
         // Find the parent of the split to close
         // This requires a parent-tracking traversal
         let result = self.remove_split_node(split_id);
+        if result.is_ok() {
+            self.changes.push(SplitChange::Closed { split_id });
+        }
 
         // If we closed the active split, update active_split to another split
         if result.is_ok() && self.active_split == split_id {
             let leaf_ids = self.root.leaf_split_ids();
             if let Some(&first_leaf) = leaf_ids.first() {
-                self.active_split = first_leaf;
+                self.focus(first_leaf);
             }
         }
 
@@ -682,7 +994,8 @@ impl SplitManager {
     pub fn adjust_ratio(&mut self, split_id: SplitId, delta: f32) -> Result<(), String> {
         if let Some(node) = self.root.find_mut(split_id) {
             if let SplitNode::Split { ratio, .. } = node {
-                *ratio = (*ratio + delta).clamp(0.1, 0.9);
+                let new_ratio = (*ratio + delta).clamp(0.1, 0.9);
+                Self::resize(&mut self.changes, split_id, ratio, new_ratio);
                 Ok(())
             } else {
                 Err("Target is not a split container".to_string())
@@ -703,7 +1016,27 @@ impl SplitManager {
             }
             // Maximized split no longer exists, clear it and fall through
         }
-        self.root.get_leaves_with_rects(viewport_rect)
+        self.visible_layout(viewport_rect).0
+    }
+
+    /// Leaves and separators of the splits that fit in `viewport_rect`,
+    /// always including the active split
+    fn visible_layout(
+        &self,
+        viewport_rect: Rect,
+    ) -> (
+        Vec<(SplitId, BufferId, Rect)>,
+        Vec<(SplitId, SplitDirection, u16, u16, u16)>,
+    ) {
+        let mut leaves = Vec::new();
+        let mut separators = Vec::new();
+        self.root.layout_visible(
+            viewport_rect,
+            self.active_split,
+            &mut leaves,
+            &mut separators,
+        );
+        (leaves, separators)
     }
 
     /// Get all split separator positions for rendering borders
@@ -713,7 +1046,11 @@ impl SplitManager {
         if self.maximized_split.is_some() {
             return vec![];
         }
-        self.root.get_separators(viewport_rect)
+        self.visible_layout(viewport_rect)
+            .1
+            .into_iter()
+            .map(|(_, dir, x, y, len)| (dir, x, y, len))
+            .collect()
     }
 
     /// Get all split separator positions with their split IDs (for mouse hit testing)
@@ -726,7 +1063,7 @@ impl SplitManager {
         if self.maximized_split.is_some() {
             return vec![];
         }
-        self.root.get_separators_with_ids(viewport_rect)
+        self.visible_layout(viewport_rect).1
     }
 
     /// Get the current ratio of a split container
@@ -746,7 +1083,8 @@ impl SplitManager {
     pub fn set_ratio(&mut self, split_id: SplitId, new_ratio: f32) -> Result<(), String> {
         if let Some(node) = self.root.find_mut(split_id) {
             if let SplitNode::Split { ratio, .. } = node {
-                *ratio = new_ratio.clamp(0.1, 0.9);
+                let new_ratio = new_ratio.clamp(0.1, 0.9);
+                Self::resize(&mut self.changes, split_id, ratio, new_ratio);
                 Ok(())
             } else {
                 Err("Target is not a split container".to_string())
@@ -759,39 +1097,52 @@ impl SplitManager {
     /// Distribute all visible splits evenly
     /// This sets the ratios of all container splits so that leaf splits get equal space
     pub fn distribute_splits_evenly(&mut self) {
-        Self::distribute_node_evenly(&mut self.root);
+        Self::distribute_node_evenly(&mut self.root, &mut self.changes);
     }
 
     /// Recursively distribute a node's splits evenly
     /// Returns the number of leaves in this subtree
-    fn distribute_node_evenly(node: &mut SplitNode) -> usize {
+    fn distribute_node_evenly(node: &mut SplitNode, changes: &mut Vec<SplitChange>) -> usize {
         match node {
             SplitNode::Leaf { .. } => 1,
             SplitNode::Split {
                 first,
                 second,
                 ratio,
+                split_id,
                 ..
             } => {
-                let first_leaves = Self::distribute_node_evenly(first);
-                let second_leaves = Self::distribute_node_evenly(second);
+                let first_leaves = Self::distribute_node_evenly(first, changes);
+                let second_leaves = Self::distribute_node_evenly(second, changes);
                 let total_leaves = first_leaves + second_leaves;
 
                 // Set ratio so each leaf gets equal space
                 // ratio = proportion for first pane
-                *ratio = (first_leaves as f32 / total_leaves as f32).clamp(0.1, 0.9);
+                let new_ratio = (first_leaves as f32 / total_leaves as f32).clamp(0.1, 0.9);
+                Self::resize(changes, *split_id, ratio, new_ratio);
 
                 total_leaves
             }
         }
     }
 
+    /// Set a container's ratio, recording the change if there is one
+    fn resize(changes: &mut Vec<SplitChange>, split_id: SplitId, ratio: &mut f32, new_ratio: f32) {
+        if *ratio != new_ratio {
+            *ratio = new_ratio;
+            changes.push(SplitChange::Resized {
+                split_id,
+                ratio: new_ratio,
+            });
+        }
+    }
+
     /// Navigate to the next split (circular)
     pub fn next_split(&mut self) {
         let leaf_ids = self.root.leaf_split_ids();
         if let Some(pos) = leaf_ids.iter().position(|id| *id == self.active_split) {
             let next_pos = (pos + 1) % leaf_ids.len();
-            self.active_split = leaf_ids[next_pos];
+            self.focus(leaf_ids[next_pos]);
         }
     }
 
@@ -804,7 +1155,7 @@ impl SplitManager {
             } else {
                 pos - 1
             };
-            self.active_split = leaf_ids[prev_pos];
+            self.focus(leaf_ids[prev_pos]);
         }
     }
 
@@ -906,6 +1257,71 @@ impl SplitManager {
             })
             .collect()
     }
+
+    /// Check the split tree's invariants, describing the first violation
+    ///
+    /// Split ids are unique and below the next id to assign, ratios lie in
+    /// 0..=1, the active and maximized splits are leaves, and every leaf shows
+    /// a buffer for which `is_open` holds.
+    pub fn check_invariants(&self, is_open: impl Fn(BufferId) -> bool) -> Result<(), String> {
+        let mut seen = std::collections::HashSet::new();
+        for split_id in self.root.all_split_ids() {
+            if !seen.insert(split_id) {
+                return Err(format!("split {:?} appears twice in the tree", split_id));
+            }
+            if split_id.0 >= self.next_split_id {
+                return Err(format!(
+                    "split {:?} is not below the next split id {}",
+                    split_id, self.next_split_id
+                ));
+            }
+        }
+
+        let mut nodes = vec![&self.root];
+        while let Some(node) = nodes.pop() {
+            match node {
+                SplitNode::Leaf {
+                    buffer_id,
+                    split_id,
+                } => {
+                    if !is_open(*buffer_id) {
+                        return Err(format!(
+                            "split {:?} shows closed buffer {:?}",
+                            split_id, buffer_id
+                        ));
+                    }
+                }
+                SplitNode::Split {
+                    first,
+                    second,
+                    ratio,
+                    split_id,
+                    ..
+                } => {
+                    if !(0.0..=1.0).contains(ratio) {
+                        return Err(format!("split {:?} has ratio {}", split_id, ratio));
+                    }
+                    nodes.push(first);
+                    nodes.push(second);
+                }
+            }
+        }
+
+        let leaves = self.root.leaf_split_ids();
+        if !leaves.contains(&self.active_split) {
+            return Err(format!(
+                "active split {:?} is not a leaf of the tree",
+                self.active_split
+            ));
+        }
+        if let Some(maximized) = self.maximized_split.filter(|id| !leaves.contains(id)) {
+            return Err(format!(
+                "maximized split {:?} is not a leaf of the tree",
+                maximized
+            ));
+        }
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -966,6 +1382,42 @@ mod tests {
         assert_eq!(manager.root().count_leaves(), 3);
     }
 
+    #[test]
+    fn test_splits_that_do_not_fit_are_hidden() {
+        let buffer_a = BufferId(0);
+        let buffer_b = BufferId(1);
+        let mut manager = SplitManager::new(buffer_a);
+        let first = manager.active_split();
+        let second = manager
+            .split_active(SplitDirection::Vertical, buffer_b, 0.5)
+            .unwrap();
+        manager.set_active_split(second);
+
+        let wide = Rect::new(0, 0, 80, 20);
+        assert_eq!(manager.get_visible_buffers(wide).len(), 2);
+        assert_eq!(manager.get_separators(wide).len(), 1);
+
+        // Too narrow for two panes: the active one takes the whole area
+        let narrow = Rect::new(0, 0, 10, 20);
+        assert_eq!(
+            manager.get_visible_buffers(narrow),
+            vec![(second, buffer_b, narrow)]
+        );
+        assert!(manager.get_separators(narrow).is_empty());
+        manager.set_active_split(first);
+        assert_eq!(
+            manager.get_visible_buffers(narrow),
+            vec![(first, buffer_a, narrow)]
+        );
+
+        // Nothing is laid out outside an empty area
+        let empty = Rect::new(3, 4, 0, 0);
+        assert_eq!(
+            manager.get_visible_buffers(empty),
+            vec![(first, buffer_a, empty)]
+        );
+    }
+
     #[test]
     fn test_close_split() {
         let buffer_a = BufferId(0);
@@ -984,6 +1436,49 @@ mod tests {
         assert_eq!(manager.root().count_leaves(), 1);
     }
 
+    #[test]
+    fn test_split_changes_are_recorded() {
+        let buffer_a = BufferId(0);
+        let buffer_b = BufferId(1);
+
+        let mut manager = SplitManager::new(buffer_a);
+        let first = manager.active_split();
+        let second = manager
+            .split_active(SplitDirection::Vertical, buffer_b, 0.5)
+            .unwrap();
+        let container = manager.root().id();
+        manager.set_ratio(container, 0.3).unwrap();
+        // Neither refocusing the active split nor an unchanged ratio is a change
+        manager.set_active_split(second);
+        manager.set_ratio(container, 0.3).unwrap();
+        manager.close_split(second).unwrap();
+
+        assert_eq!(
+            manager.take_changes(),
+            vec![
+                SplitChange::Created {
+                    split_id: second,
+                    parent_id: container,
+                    direction: SplitDirection::Vertical,
+                },
+                SplitChange::Focused {
+                    split_id: second,
+                    previous_split_id: first,
+                },
+                SplitChange::Resized {
+                    split_id: container,
+                    ratio: 0.3,
+                },
+                SplitChange::Closed { split_id: second },
+                SplitChange::Focused {
+                    split_id: first,
+                    previous_split_id: second,
+                },
+            ]
+        );
+        assert!(manager.take_changes().is_empty());
+    }
+
     #[test]
     fn test_cannot_close_last_split() {
         let buffer_a = BufferId(0);
@@ -993,6 +1488,30 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_check_invariants() {
+        let buffer_a = BufferId(0);
+        let buffer_b = BufferId(1);
+
+        let mut manager = SplitManager::new(buffer_a);
+        let new_split = manager
+            .split_active(SplitDirection::Horizontal, buffer_b, 0.5)
+            .unwrap();
+        assert!(manager.check_invariants(|_| true).is_ok());
+
+        let err = manager.check_invariants(|id| id != buffer_b).unwrap_err();
+        assert!(
+            err.contains(&format!("split {:?} shows closed buffer", new_split)),
+            "{}",
+            err
+        );
+
+        // A container split cannot be active
+        manager.active_split = manager.root().id();
+        let err = manager.check_invariants(|_| true).unwrap_err();
+        assert!(err.contains("is not a leaf"), "{}", err);
+    }
+
     #[test]
     fn test_split_rect_horizontal() {
         let rect = Rect {
@@ -1032,4 +1551,55 @@ mod tests {
         assert_eq!(first.x, 0);
         assert_eq!(second.x, 51); // first.x + first.width + 1 (separator)
     }
+
+    #[test]
+    fn test_retained_view_restores_unchanged_buffer() {
+        let mut view_state = SplitViewState::with_buffer(80, 24, BufferId(1));
+        view_state.viewport.top_byte = 500;
+        view_state.viewport.left_column = 4;
+        view_state.retain_view(BufferId(1), 7);
+        view_state.viewport.top_byte = 0;
+        view_state.viewport.left_column = 0;
+        assert_eq!(view_state.retained_views.len(), 1);
+
+        assert!(view_state.restore_view(BufferId(1), 7));
+        assert_eq!(view_state.viewport.top_byte, 500);
+        assert_eq!(view_state.viewport.left_column, 4);
+        assert!(view_state.retained_views.is_empty());
+    }
+
+    #[test]
+    fn test_retained_view_of_changed_or_closed_buffer_is_dropped() {
+        let mut view_state = SplitViewState::with_buffer(80, 24, BufferId(1));
+        view_state.viewport.top_byte = 500;
+        view_state.retain_view(BufferId(1), 7);
+        view_state.viewport.top_byte = 0;
+
+        assert!(!view_state.restore_view(BufferId(1), 8));
+        assert_eq!(view_state.viewport.top_byte, 0);
+        assert!(view_state.retained_views.is_empty());
+
+        view_state.retain_view(BufferId(2), 3);
+        view_state.remove_buffer(BufferId(2));
+        assert!(!view_state.restore_view(BufferId(2), 3));
+    }
+
+    #[test]
+    fn test_shrinking_retained_views_drops_least_recently_left() {
+        let mut view_state = SplitViewState::with_buffer(80, 24, BufferId(1));
+        for id in 1..=3 {
+            view_state.retain_view(BufferId(id), 1);
+        }
+        let views = &mut view_state.retained_views;
+        let per_view = views.approx_bytes() / 3;
+
+        views.shrink(2 * per_view);
+        assert_eq!(views.len(), 2);
+        assert_eq!(views.approx_bytes(), 2 * per_view);
+
+        // A dropped view is laid out again instead of restored
+        assert!(!view_state.restore_view(BufferId(1), 1));
+        assert!(view_state.restore_view(BufferId(2), 1));
+        assert_eq!(view_state.retained_views.hits_and_misses(), (1, 1));
+    }
 }
@@ -0,0 +1,209 @@
+//! Terminal side effects outside the drawn frame
+//!
+//! The window title, the cursor shape, OSC 52 clipboard copies, the
+//! background color query and the terminal's input modes are set with
+//! escape sequences written next to ratatui's output rather than into its
+//! buffer. Written at arbitrary points of an event loop iteration, such a
+//! sequence can end up in the middle of a frame on a slow terminal. Instead, the editor queues them as
+//! [`TerminalEffect`]s and the event loop writes the queue with
+//! [`write_effects`] once per iteration, right before `terminal.draw`, as one
+//! buffered write.
+//!
+//! An effect replaces any queued effect of the same kind, so a frame sets the
+//! title or the cursor shape at most once, with the last value asked for. The
+//! exit and panic paths restore the terminal with [`restore_effects`] through
+//! the same function.
+
+use std::io::{self, Write};
+use std::mem::discriminant;
+
+use crossterm::clipboard::CopyToClipboard;
+use crossterm::event::{
+    DisableBracketedPaste, DisableFocusChange, DisableMouseCapture, EnableBracketedPaste,
+    EnableFocusChange, EnableMouseCapture, KeyboardEnhancementFlags, PopKeyboardEnhancementFlags,
+    PushKeyboardEnhancementFlags,
+};
+use crossterm::queue;
+use crossterm::terminal::SetTitle;
+
+use crate::config::CursorStyle;
+use crate::primitives::display_text::{sanitize_line, MAX_LABEL_CHARS};
+
+/// An escape sequence for the terminal, by what it changes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TerminalEffect {
+    /// Set the window title
+    SetTitle(String),
+    /// Set the shape of the hardware cursor
+    SetCursorStyle(CursorStyle),
+    /// Put text on the system clipboard with OSC 52
+    Osc52Copy(String),
+    /// Turn mouse reporting on or off
+    MouseCapture(bool),
+    /// Turn bracketed paste on or off
+    BracketedPaste(bool),
+    /// Turn focus gained/lost reports on or off
+    FocusEvents(bool),
+    /// Push keyboard enhancement flags
+    PushKeyboardFlags(KeyboardEnhancementFlags),
+    /// Pop the keyboard enhancement flags pushed last
+    PopKeyboardFlags,
+    /// Ask for the background color with OSC 11; the reply comes in as keys,
+    /// see [`crate::input::terminal_reply`]
+    QueryBackground,
+}
+
+impl TerminalEffect {
+    /// Whether this effect makes a queued `other` pointless
+    ///
+    /// Keyboard flags form a stack on the terminal's side, so every push and
+    /// pop is written.
+    fn replaces(&self, other: &TerminalEffect) -> bool {
+        let stacked = |effect: &TerminalEffect| {
+            matches!(effect, Self::PushKeyboardFlags(_) | Self::PopKeyboardFlags)
+        };
+        !stacked(self) && discriminant(self) == discriminant(other)
+    }
+
+    /// Append the escape sequence of this effect to `out`
+    fn encode(&self, out: &mut Vec<u8>) -> io::Result<()> {
+        match self {
+            Self::SetTitle(title) => queue!(out, SetTitle(title)),
+            Self::SetCursorStyle(style) => queue!(out, style.to_crossterm_style()),
+            Self::Osc52Copy(text) => queue!(out, CopyToClipboard::to_clipboard_from(text)),
+            Self::MouseCapture(true) => queue!(out, EnableMouseCapture),
+            Self::MouseCapture(false) => queue!(out, DisableMouseCapture),
+            Self::BracketedPaste(true) => queue!(out, EnableBracketedPaste),
+            Self::BracketedPaste(false) => queue!(out, DisableBracketedPaste),
+            Self::FocusEvents(true) => queue!(out, EnableFocusChange),
+            Self::FocusEvents(false) => queue!(out, DisableFocusChange),
+            Self::PushKeyboardFlags(flags) => queue!(out, PushKeyboardEnhancementFlags(*flags)),
+            Self::PopKeyboardFlags => queue!(out, PopKeyboardEnhancementFlags),
+            Self::QueryBackground => out.write_all(b"\x1b]11;?\x1b\\"),
+        }
+    }
+}
+
+/// Effects waiting for the next frame
+#[derive(Debug, Default)]
+pub struct TerminalEffects {
+    queue: Vec<TerminalEffect>,
+}
+
+impl TerminalEffects {
+    /// Queue `effect`, dropping a queued one it replaces
+    pub fn push(&mut self, effect: TerminalEffect) {
+        let effect = match effect {
+            // A title can't hold control characters: BEL would end the
+            // sequence early
+            TerminalEffect::SetTitle(title) => {
+                TerminalEffect::SetTitle(sanitize_line(&title, MAX_LABEL_CHARS, "…").into_owned())
+            }
+            effect => effect,
+        };
+        self.queue.retain(|queued| !effect.replaces(queued));
+        self.queue.push(effect);
+    }
+
+    /// Whether nothing is queued
+    pub fn is_empty(&self) -> bool {
+        self.queue.is_empty()
+    }
+
+    /// The queued effects in the order they were queued, emptying the queue
+    pub fn take(&mut self) -> Vec<TerminalEffect> {
+        std::mem::take(&mut self.queue)
+    }
+}
+
+/// Write `effects` to `out` in one write, then flush it
+pub fn write_effects(out: &mut impl Write, effects: &[TerminalEffect]) -> io::Result<()> {
+    if effects.is_empty() {
+        return Ok(());
+    }
+    let mut bytes = Vec::new();
+    for effect in effects {
+        effect.encode(&mut bytes)?;
+    }
+    out.write_all(&bytes)?;
+    out.flush()
+}
+
+/// Effects putting the terminal back as it was before the editor started,
+/// with the cursor shape `cursor`
+pub fn restore_effects(cursor: CursorStyle) -> Vec<TerminalEffect> {
+    vec![
+        TerminalEffect::MouseCapture(false),
+        TerminalEffect::BracketedPaste(false),
+        TerminalEffect::FocusEvents(false),
+        TerminalEffect::SetCursorStyle(cursor),
+        TerminalEffect::PopKeyboardFlags,
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn written(effects: &[TerminalEffect]) -> String {
+        let mut out = Vec::new();
+        write_effects(&mut out, effects).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_later_effect_replaces_same_kind() {
+        let mut effects = TerminalEffects::default();
+        effects.push(TerminalEffect::SetCursorStyle(CursorStyle::SteadyBar));
+        effects.push(TerminalEffect::MouseCapture(false));
+        effects.push(TerminalEffect::SetCursorStyle(CursorStyle::SteadyBlock));
+        effects.push(TerminalEffect::MouseCapture(true));
+        assert_eq!(
+            effects.take(),
+            vec![
+                TerminalEffect::SetCursorStyle(CursorStyle::SteadyBlock),
+                TerminalEffect::MouseCapture(true),
+            ]
+        );
+        assert!(effects.is_empty());
+    }
+
+    #[test]
+    fn test_keyboard_flags_are_all_kept() {
+        let mut effects = TerminalEffects::default();
+        let flags = KeyboardEnhancementFlags::DISAMBIGUATE_ESCAPE_CODES;
+        effects.push(TerminalEffect::PushKeyboardFlags(flags));
+        effects.push(TerminalEffect::PopKeyboardFlags);
+        effects.push(TerminalEffect::PushKeyboardFlags(flags));
+        assert_eq!(effects.take().len(), 3);
+    }
+
+    #[test]
+    fn test_rapid_title_changes_write_one_title_per_frame() {
+        let mut effects = TerminalEffects::default();
+        for i in 0..1000 {
+            effects.push(TerminalEffect::SetTitle(format!("status {}", i)));
+        }
+        let out = written(&effects.take());
+        assert_eq!(out.matches("\x1b]0;").count(), 1);
+        assert_eq!(out, "\x1b]0;status 999\x07");
+    }
+
+    #[test]
+    fn test_title_control_characters_are_replaced() {
+        let mut effects = TerminalEffects::default();
+        effects.push(TerminalEffect::SetTitle("a\x07\x1b]0;b".to_string()));
+        assert_eq!(written(&effects.take()), "\x1b]0;a<07><1B>]0;b\x07");
+    }
+
+    #[test]
+    fn test_effects_are_written_in_order() {
+        let out = written(&[
+            TerminalEffect::SetCursorStyle(CursorStyle::SteadyBar),
+            TerminalEffect::FocusEvents(true),
+            TerminalEffect::BracketedPaste(false),
+            TerminalEffect::QueryBackground,
+        ]);
+        assert_eq!(out, "\x1b[6 q\x1b[?1004h\x1b[?2004l\x1b]11;?\x1b\\");
+    }
+}
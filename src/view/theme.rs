@@ -1,6 +1,8 @@
-use ratatui::style::Color;
+use ratatui::style::{Color, Modifier, Style};
 use serde::{Deserialize, Serialize};
-use std::path::Path;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
 
 /// Serializable color representation
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -10,12 +12,16 @@ enum ColorDef {
     Rgb(u8, u8, u8),
     /// Named color
     Named(String),
+    /// `null`: no color of its own, so the terminal's background shows
+    /// through (same as "terminal")
+    Terminal,
 }
 
 impl From<ColorDef> for Color {
     fn from(def: ColorDef) -> Self {
         match def {
             ColorDef::Rgb(r, g, b) => Color::Rgb(r, g, b),
+            ColorDef::Terminal => Color::Reset,
             ColorDef::Named(name) => match name.as_str() {
                 "Black" => Color::Black,
                 "Red" => Color::Red,
@@ -34,7 +40,7 @@ impl From<ColorDef> for Color {
                 "LightCyan" => Color::LightCyan,
                 "White" => Color::White,
                 // Default/Reset uses the terminal's default color (preserves transparency)
-                "Default" | "Reset" => Color::Reset,
+                "Default" | "Reset" | "terminal" | "Terminal" => Color::Reset,
                 _ => Color::White, // Default fallback
             },
         }
@@ -50,6 +56,13 @@ struct ThemeFile {
     search: SearchColors,
     diagnostic: DiagnosticColors,
     syntax: SyntaxColors,
+    #[serde(default)]
+    whitespace: WhitespaceColors,
+    #[serde(default)]
+    diff: DiffColors,
+    /// Overrides for entries of the glyph table, by name
+    #[serde(default)]
+    glyphs: HashMap<String, String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -63,11 +76,21 @@ struct EditorColors {
     current_line_bg: ColorDef,
     line_number_fg: ColorDef,
     line_number_bg: ColorDef,
+    #[serde(default = "default_ruler_bg")]
+    ruler_bg: ColorDef,
+    #[serde(default = "default_overlength_bg")]
+    overlength_bg: ColorDef,
 }
 
 fn default_inactive_cursor() -> ColorDef {
     ColorDef::Named("DarkGray".to_string())
 }
+fn default_ruler_bg() -> ColorDef {
+    ColorDef::Rgb(42, 42, 42) // Slightly lighter than the dark editor_bg
+}
+fn default_overlength_bg() -> ColorDef {
+    ColorDef::Rgb(70, 40, 40) // Subtle red tint
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 struct UiColors {
@@ -312,6 +335,104 @@ struct SyntaxColors {
     operator: ColorDef,
 }
 
+/// Added, removed and modified lines in diffs and change markers
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+struct DiffColors {
+    added_fg: ColorDef,
+    added_bg: ColorDef,
+    removed_fg: ColorDef,
+    removed_bg: ColorDef,
+    modified_fg: ColorDef,
+    modified_bg: ColorDef,
+}
+
+impl Default for DiffColors {
+    fn default() -> Self {
+        Self {
+            added_fg: ColorDef::Rgb(80, 250, 123),
+            added_bg: ColorDef::Rgb(35, 70, 35),
+            removed_fg: ColorDef::Rgb(255, 85, 85),
+            removed_bg: ColorDef::Rgb(80, 35, 35),
+            modified_fg: ColorDef::Rgb(255, 184, 108),
+            modified_bg: ColorDef::Rgb(35, 45, 80),
+        }
+    }
+}
+
+/// Visible whitespace; unset entries fall back to the default symbols and
+/// to the line number and diagnostic colors
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct WhitespaceColors {
+    #[serde(default)]
+    space: Option<WhitespaceGlyphDef>,
+    #[serde(default)]
+    tab: Option<WhitespaceGlyphDef>,
+    #[serde(default)]
+    unusual_space: Option<WhitespaceGlyphDef>,
+    #[serde(default)]
+    trailing_bg: Option<ColorDef>,
+    #[serde(default)]
+    control_fg: Option<ColorDef>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct WhitespaceGlyphDef {
+    #[serde(default)]
+    symbol: Option<char>,
+    #[serde(default)]
+    fg: Option<ColorDef>,
+}
+
+impl WhitespaceGlyphDef {
+    fn resolve(def: Option<Self>, default: WhitespaceGlyph) -> WhitespaceGlyph {
+        let Some(def) = def else {
+            return default;
+        };
+        WhitespaceGlyph {
+            symbol: def.symbol.unwrap_or(default.symbol),
+            fg: def.fg.map_or(default.fg, Color::from),
+        }
+    }
+}
+
+/// Symbol and color drawn in place of a whitespace character
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WhitespaceGlyph {
+    pub symbol: char,
+    pub fg: Color,
+}
+
+/// How visible whitespace and control character placeholders are drawn
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WhitespaceTheme {
+    pub space: WhitespaceGlyph,
+    /// Drawn in the first cell of a tab; the rest of the tab stays blank
+    pub tab: WhitespaceGlyph,
+    /// Non-breaking and other unusual Unicode spaces
+    pub unusual_space: WhitespaceGlyph,
+    /// Background of whitespace at the end of a line
+    pub trailing_bg: Color,
+    /// Color of `<XX>` control character placeholders
+    pub control_fg: Color,
+}
+
+impl WhitespaceTheme {
+    /// Default symbols in the given colors
+    pub fn new(fg: Color, unusual_fg: Color, trailing_bg: Color, control_fg: Color) -> Self {
+        Self {
+            space: WhitespaceGlyph { symbol: '·', fg },
+            tab: WhitespaceGlyph { symbol: '→', fg },
+            unusual_space: WhitespaceGlyph {
+                symbol: '⍽',
+                fg: unusual_fg,
+            },
+            trailing_bg,
+            control_fg,
+        }
+    }
+}
+
 /// Comprehensive theme structure with all UI colors
 #[derive(Debug, Clone)]
 pub struct Theme {
@@ -327,6 +448,10 @@ pub struct Theme {
     pub current_line_bg: Color,
     pub line_number_fg: Color,
     pub line_number_bg: Color,
+    /// Background of the ruler columns (`ui.rulers`)
+    pub ruler_bg: Color,
+    /// Background of text past the last ruler (`ui.highlight_overlength`)
+    pub overlength_bg: Color,
 
     // UI element colors
     pub tab_active_fg: Color,
@@ -435,11 +560,47 @@ pub struct Theme {
     pub syntax_variable: Color,
     pub syntax_constant: Color,
     pub syntax_operator: Color,
+
+    // Diff colors (also used for change markers in the gutter)
+    pub diff_added_fg: Color,
+    pub diff_added_bg: Color,
+    pub diff_removed_fg: Color,
+    pub diff_removed_bg: Color,
+    pub diff_modified_fg: Color,
+    pub diff_modified_bg: Color,
+
+    // Visible whitespace
+    pub whitespace: WhitespaceTheme,
+
+    /// Overrides for entries of the glyph table (see [`crate::view::glyphs`])
+    pub glyphs: HashMap<String, String>,
 }
 
 impl From<ThemeFile> for Theme {
     fn from(file: ThemeFile) -> Self {
-        Self {
+        let defaults = WhitespaceTheme::new(
+            file.editor.line_number_fg.clone().into(),
+            file.diagnostic.warning_fg.clone().into(),
+            file.diagnostic.error_bg.clone().into(),
+            file.diagnostic.warning_fg.clone().into(),
+        );
+        let whitespace = WhitespaceTheme {
+            space: WhitespaceGlyphDef::resolve(file.whitespace.space, defaults.space),
+            tab: WhitespaceGlyphDef::resolve(file.whitespace.tab, defaults.tab),
+            unusual_space: WhitespaceGlyphDef::resolve(
+                file.whitespace.unusual_space,
+                defaults.unusual_space,
+            ),
+            trailing_bg: file
+                .whitespace
+                .trailing_bg
+                .map_or(defaults.trailing_bg, Color::from),
+            control_fg: file
+                .whitespace
+                .control_fg
+                .map_or(defaults.control_fg, Color::from),
+        };
+        let mut theme = Self {
             name: file.name,
             editor_bg: file.editor.bg.into(),
             editor_fg: file.editor.fg.into(),
@@ -449,6 +610,8 @@ impl From<ThemeFile> for Theme {
             current_line_bg: file.editor.current_line_bg.into(),
             line_number_fg: file.editor.line_number_fg.into(),
             line_number_bg: file.editor.line_number_bg.into(),
+            ruler_bg: file.editor.ruler_bg.into(),
+            overlength_bg: file.editor.overlength_bg.into(),
             tab_active_fg: file.ui.tab_active_fg.into(),
             tab_active_bg: file.ui.tab_active_bg.into(),
             tab_inactive_fg: file.ui.tab_inactive_fg.into(),
@@ -527,13 +690,77 @@ impl From<ThemeFile> for Theme {
             syntax_variable: file.syntax.variable.into(),
             syntax_constant: file.syntax.constant.into(),
             syntax_operator: file.syntax.operator.into(),
-        }
+            diff_added_fg: file.diff.added_fg.into(),
+            diff_added_bg: file.diff.added_bg.into(),
+            diff_removed_fg: file.diff.removed_fg.into(),
+            diff_removed_bg: file.diff.removed_bg.into(),
+            diff_modified_fg: file.diff.modified_fg.into(),
+            diff_modified_bg: file.diff.modified_bg.into(),
+            whitespace,
+            glyphs: file.glyphs,
+        };
+        theme.ensure_opaque_backgrounds();
+        theme
     }
 }
 
 impl Theme {
+    /// Leave the editor background and gutter to the terminal
+    /// (`ui.transparent_background`)
+    pub fn make_background_transparent(&mut self) {
+        self.editor_bg = Color::Reset;
+        self.line_number_bg = Color::Reset;
+    }
+
+    /// Give the backgrounds that text must stay readable on a color of their
+    /// own, taken from the dark theme, when the theme file leaves them to
+    /// the terminal: selection, current line, popups, completions, menus
+    /// and help
+    fn ensure_opaque_backgrounds(&mut self) {
+        let dark = Self::dark();
+        let backgrounds = [
+            ("selection_bg", &mut self.selection_bg, dark.selection_bg),
+            (
+                "current_line_bg",
+                &mut self.current_line_bg,
+                dark.current_line_bg,
+            ),
+            ("popup_bg", &mut self.popup_bg, dark.popup_bg),
+            (
+                "popup_selection_bg",
+                &mut self.popup_selection_bg,
+                dark.popup_selection_bg,
+            ),
+            ("suggestion_bg", &mut self.suggestion_bg, dark.suggestion_bg),
+            (
+                "suggestion_selected_bg",
+                &mut self.suggestion_selected_bg,
+                dark.suggestion_selected_bg,
+            ),
+            (
+                "menu_dropdown_bg",
+                &mut self.menu_dropdown_bg,
+                dark.menu_dropdown_bg,
+            ),
+            ("help_bg", &mut self.help_bg, dark.help_bg),
+        ];
+        for (name, color, fallback) in backgrounds {
+            if *color == Color::Reset {
+                tracing::warn!(
+                    "Theme '{}': {} can't be the terminal background, using {:?}",
+                    self.name,
+                    name,
+                    fallback
+                );
+                *color = fallback;
+            }
+        }
+    }
+
     /// Load theme from a JSON file
-    fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, String> {
+    ///
+    /// Parse errors include the line and column of the problem.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, String> {
         let content = std::fs::read_to_string(path)
             .map_err(|e| format!("Failed to read theme file: {}", e))?;
         let theme_file: ThemeFile = serde_json::from_str(&content)
@@ -541,31 +768,42 @@ impl Theme {
         Ok(theme_file.into())
     }
 
-    /// Load builtin theme from the themes directory
-    fn load_builtin_theme(name: &str) -> Option<Self> {
-        // Build list of paths to search
-        let mut theme_paths = vec![
-            format!("themes/{}.json", name),
-            format!("../themes/{}.json", name),
-            format!("../../themes/{}.json", name),
-        ];
+    /// User themes directory in the platform's default config location
+    fn default_user_themes_dir() -> Option<PathBuf> {
+        dirs::config_dir().map(|config_dir| config_dir.join("fresh").join("themes"))
+    }
 
-        // Also check user config themes directory
-        if let Some(config_dir) = dirs::config_dir() {
-            let user_theme_path = config_dir
-                .join("fresh")
-                .join("themes")
-                .join(format!("{}.json", name));
-            theme_paths.insert(0, user_theme_path.to_string_lossy().to_string());
+    /// Files a theme named `name` may be loaded from, in search order
+    fn theme_file_candidates(name: &str, user_themes_dir: Option<&Path>) -> Vec<PathBuf> {
+        let file_name = format!("{}.json", name);
+        let mut theme_paths: Vec<PathBuf> = ["themes", "../themes", "../../themes"]
+            .iter()
+            .map(|dir| Path::new(dir).join(&file_name))
+            .collect();
+
+        // User config themes directory takes precedence
+        if let Some(user_themes_dir) = user_themes_dir {
+            theme_paths.insert(0, user_themes_dir.join(&file_name));
         }
+        theme_paths
+    }
 
-        for path in &theme_paths {
-            if let Ok(theme) = Self::from_file(path) {
-                return Some(theme);
-            }
-        }
+    /// Load builtin theme from the themes directory
+    fn load_builtin_theme(name: &str, user_themes_dir: Option<&Path>) -> Option<Self> {
+        Self::theme_file_candidates(name, user_themes_dir)
+            .iter()
+            .find_map(|path| Self::from_file(path).ok())
+    }
 
-        None
+    /// JSON file the theme named `name` is loaded from, if it has one
+    ///
+    /// Hardcoded fallback themes have no source file.
+    pub fn source_path_in(name: &str, user_themes_dir: Option<&Path>) -> Option<PathBuf> {
+        let normalized_name = name.to_lowercase().replace('_', "-");
+        Self::theme_file_candidates(&normalized_name, user_themes_dir)
+            .into_iter()
+            .find(|path| path.is_file())
+            .map(|path| path.canonicalize().unwrap_or(path))
     }
 
     /// Default dark theme (VSCode Dark+ inspired)
@@ -583,6 +821,8 @@ impl Theme {
             current_line_bg: Color::Rgb(40, 40, 40),
             line_number_fg: Color::Rgb(100, 100, 100),
             line_number_bg: Color::Rgb(30, 30, 30),
+            ruler_bg: Color::Rgb(42, 42, 42),
+            overlength_bg: Color::Rgb(70, 40, 40),
 
             // UI element colors
             tab_active_fg: Color::Yellow,
@@ -690,6 +930,23 @@ impl Theme {
             syntax_variable: Color::Rgb(156, 220, 254),
             syntax_constant: Color::Rgb(79, 193, 255),
             syntax_operator: Color::Rgb(212, 212, 212),
+
+            // Diff colors
+            diff_added_fg: Color::Rgb(80, 250, 123),
+            diff_added_bg: Color::Rgb(35, 70, 35),
+            diff_removed_fg: Color::Rgb(255, 85, 85),
+            diff_removed_bg: Color::Rgb(80, 35, 35),
+            diff_modified_fg: Color::Rgb(255, 184, 108),
+            diff_modified_bg: Color::Rgb(35, 45, 80),
+
+            // Visible whitespace
+            whitespace: WhitespaceTheme::new(
+                Color::Rgb(80, 80, 80),
+                Color::Rgb(230, 140, 60),
+                Color::Rgb(90, 35, 35),
+                Color::Rgb(220, 170, 70),
+            ),
+            glyphs: HashMap::new(),
         }
     }
 
@@ -707,6 +964,8 @@ impl Theme {
             current_line_bg: Color::Rgb(245, 245, 245),
             line_number_fg: Color::Rgb(140, 140, 140),
             line_number_bg: Color::Rgb(255, 255, 255),
+            ruler_bg: Color::Rgb(238, 238, 238),
+            overlength_bg: Color::Rgb(255, 226, 226),
 
             // UI element colors
             tab_active_fg: Color::Rgb(40, 40, 40),
@@ -814,6 +1073,23 @@ impl Theme {
             syntax_variable: Color::Rgb(0, 16, 128), // Dark blue variables
             syntax_constant: Color::Rgb(0, 112, 193), // Blue constants
             syntax_operator: Color::Rgb(0, 0, 0),    // Black operators
+
+            // Diff colors
+            diff_added_fg: Color::Rgb(40, 150, 60),
+            diff_added_bg: Color::Rgb(220, 250, 220),
+            diff_removed_fg: Color::Rgb(200, 40, 40),
+            diff_removed_bg: Color::Rgb(255, 225, 225),
+            diff_modified_fg: Color::Rgb(190, 120, 0),
+            diff_modified_bg: Color::Rgb(220, 230, 255),
+
+            // Visible whitespace
+            whitespace: WhitespaceTheme::new(
+                Color::Rgb(190, 190, 190),
+                Color::Rgb(200, 90, 0),
+                Color::Rgb(255, 215, 215),
+                Color::Rgb(170, 95, 0),
+            ),
+            glyphs: HashMap::new(),
         }
     }
 
@@ -831,6 +1107,8 @@ impl Theme {
             current_line_bg: Color::Rgb(20, 20, 20),
             line_number_fg: Color::Rgb(140, 140, 140),
             line_number_bg: Color::Black,
+            ruler_bg: Color::Rgb(40, 40, 40),
+            overlength_bg: Color::Rgb(90, 0, 0),
 
             // UI element colors
             tab_active_fg: Color::Black,
@@ -938,19 +1216,51 @@ impl Theme {
             syntax_variable: Color::White,
             syntax_constant: Color::LightBlue,
             syntax_operator: Color::White,
+
+            // Diff colors
+            diff_added_fg: Color::Rgb(0, 255, 0),
+            diff_added_bg: Color::Rgb(0, 80, 0),
+            diff_removed_fg: Color::Rgb(255, 0, 0),
+            diff_removed_bg: Color::Rgb(100, 0, 0),
+            diff_modified_fg: Color::Rgb(255, 255, 0),
+            diff_modified_bg: Color::Rgb(0, 0, 120),
+
+            // Visible whitespace
+            whitespace: WhitespaceTheme::new(
+                Color::DarkGray,
+                Color::LightRed,
+                Color::Rgb(128, 0, 0),
+                Color::Yellow,
+            ),
+            glyphs: HashMap::new(),
         }
     }
 
     /// Get a theme by name, defaults to dark if not found
     /// Tries to load from JSON file first, falls back to hardcoded themes
+    ///
+    /// Any theme name followed by [`HIGH_CONTRAST_SUFFIX`] gives that
+    /// theme's [`Theme::high_contrast_variant`].
     pub fn from_name(name: &str) -> Self {
+        Self::from_name_in(name, Self::default_user_themes_dir().as_deref())
+    }
+
+    /// Like [`Theme::from_name`], looking for user themes in `user_themes_dir`
+    pub fn from_name_in(name: &str, user_themes_dir: Option<&Path>) -> Self {
         let normalized_name = name.to_lowercase().replace('_', "-");
 
         // Try to load from JSON file first
-        if let Some(theme) = Self::load_builtin_theme(&normalized_name) {
+        if let Some(theme) = Self::load_builtin_theme(&normalized_name, user_themes_dir) {
             return theme;
         }
 
+        // Generated high-contrast variant of another theme
+        if let Some(base) = normalized_name.strip_suffix(HIGH_CONTRAST_SUFFIX) {
+            if !base.is_empty() {
+                return Self::from_name_in(base, user_themes_dir).high_contrast_variant();
+            }
+        }
+
         // Fall back to hardcoded themes
         match normalized_name.as_str() {
             "light" => Self::light(),
@@ -962,17 +1272,23 @@ impl Theme {
 
     /// Get all available theme names (builtin + user themes)
     pub fn available_themes() -> Vec<String> {
+        Self::available_themes_in(Self::default_user_themes_dir().as_deref())
+    }
+
+    /// Like [`Theme::available_themes`], looking for user themes in `user_themes_dir`
+    pub fn available_themes_in(user_themes_dir: Option<&Path>) -> Vec<String> {
         let mut themes: Vec<String> = vec![
             "dark".to_string(),
             "light".to_string(),
             "high-contrast".to_string(),
             "nostalgia".to_string(),
+            format!("dark{}", HIGH_CONTRAST_SUFFIX),
+            format!("light{}", HIGH_CONTRAST_SUFFIX),
         ];
 
         // Scan user themes directory
-        if let Some(config_dir) = dirs::config_dir() {
-            let user_themes_dir = config_dir.join("fresh").join("themes");
-            if let Ok(entries) = std::fs::read_dir(&user_themes_dir) {
+        if let Some(user_themes_dir) = user_themes_dir {
+            if let Ok(entries) = std::fs::read_dir(user_themes_dir) {
                 for entry in entries.flatten() {
                     let path = entry.path();
                     if path.extension().is_some_and(|ext| ext == "json") {
@@ -1005,6 +1321,8 @@ impl Theme {
             current_line_bg: Color::Rgb(0, 0, 128),  // Slightly darker blue
             line_number_fg: Color::Rgb(85, 255, 255), // Cyan
             line_number_bg: Color::Rgb(0, 0, 170),
+            ruler_bg: Color::Rgb(0, 0, 140),
+            overlength_bg: Color::Rgb(128, 0, 0),
 
             // UI element colors
             tab_active_fg: Color::Rgb(0, 0, 0),
@@ -1112,10 +1430,821 @@ impl Theme {
             syntax_variable: Color::Rgb(255, 255, 85), // Yellow variables
             syntax_constant: Color::Rgb(255, 0, 255),  // Bright magenta constants
             syntax_operator: Color::Rgb(170, 170, 170), // Light gray operators
+
+            // Diff colors
+            diff_added_fg: Color::Rgb(85, 255, 85),
+            diff_added_bg: Color::Rgb(0, 85, 0),
+            diff_removed_fg: Color::Rgb(255, 85, 85),
+            diff_removed_bg: Color::Rgb(120, 0, 0),
+            diff_modified_fg: Color::Rgb(255, 255, 85),
+            diff_modified_bg: Color::Rgb(85, 0, 170),
+
+            // Visible whitespace
+            whitespace: WhitespaceTheme::new(
+                Color::Rgb(85, 85, 255),
+                Color::Rgb(255, 85, 85),
+                Color::Rgb(170, 0, 0),
+                Color::Rgb(255, 255, 85),
+            ),
+            glyphs: HashMap::new(),
         }
     }
 }
 
+/// Short names for theme colors, on top of their theme file paths
+const COLOR_ALIASES: &[(&str, &str)] = &[
+    ("accent", "editor.cursor"),
+    ("selection", "editor.selection_bg"),
+    ("search.match", "search.match_bg"),
+    ("diagnostic.error", "diagnostic.error_fg"),
+    ("diagnostic.warning", "diagnostic.warning_fg"),
+    ("diagnostic.info", "diagnostic.info_fg"),
+    ("diagnostic.hint", "diagnostic.hint_fg"),
+    ("diff.added", "diff.added_fg"),
+    ("diff.removed", "diff.removed_fg"),
+    ("diff.modified", "diff.modified_fg"),
+];
+
+/// Theme colors by their path in theme files
+const THEME_COLOR_PATHS: &[&str] = &[
+    "editor.bg",
+    "editor.fg",
+    "editor.cursor",
+    "editor.inactive_cursor",
+    "editor.selection_bg",
+    "editor.current_line_bg",
+    "editor.line_number_fg",
+    "editor.line_number_bg",
+    "editor.ruler_bg",
+    "editor.overlength_bg",
+    "ui.tab_active_fg",
+    "ui.tab_active_bg",
+    "ui.tab_inactive_fg",
+    "ui.tab_inactive_bg",
+    "ui.tab_separator_bg",
+    "ui.tab_close_hover_fg",
+    "ui.tab_hover_bg",
+    "ui.menu_bg",
+    "ui.menu_fg",
+    "ui.menu_active_bg",
+    "ui.menu_active_fg",
+    "ui.menu_dropdown_bg",
+    "ui.menu_dropdown_fg",
+    "ui.menu_highlight_bg",
+    "ui.menu_highlight_fg",
+    "ui.menu_border_fg",
+    "ui.menu_separator_fg",
+    "ui.menu_hover_bg",
+    "ui.menu_hover_fg",
+    "ui.menu_disabled_fg",
+    "ui.menu_disabled_bg",
+    "ui.status_bar_fg",
+    "ui.status_bar_bg",
+    "ui.prompt_fg",
+    "ui.prompt_bg",
+    "ui.prompt_selection_fg",
+    "ui.prompt_selection_bg",
+    "ui.popup_border_fg",
+    "ui.popup_bg",
+    "ui.popup_selection_bg",
+    "ui.popup_text_fg",
+    "ui.suggestion_bg",
+    "ui.suggestion_selected_bg",
+    "ui.help_bg",
+    "ui.help_fg",
+    "ui.help_key_fg",
+    "ui.help_separator_fg",
+    "ui.help_indicator_fg",
+    "ui.help_indicator_bg",
+    "ui.inline_code_bg",
+    "ui.split_separator_fg",
+    "ui.split_separator_hover_fg",
+    "ui.scrollbar_track_fg",
+    "ui.scrollbar_thumb_fg",
+    "ui.scrollbar_track_hover_fg",
+    "ui.scrollbar_thumb_hover_fg",
+    "ui.compose_margin_bg",
+    "ui.semantic_highlight_bg",
+    "ui.terminal_bg",
+    "ui.terminal_fg",
+    "ui.status_warning_indicator_bg",
+    "ui.status_warning_indicator_fg",
+    "ui.status_error_indicator_bg",
+    "ui.status_error_indicator_fg",
+    "ui.status_warning_indicator_hover_bg",
+    "ui.status_warning_indicator_hover_fg",
+    "ui.status_error_indicator_hover_bg",
+    "ui.status_error_indicator_hover_fg",
+    "ui.tab_drop_zone_bg",
+    "ui.tab_drop_zone_border",
+    "search.match_bg",
+    "search.match_fg",
+    "diagnostic.error_fg",
+    "diagnostic.error_bg",
+    "diagnostic.warning_fg",
+    "diagnostic.warning_bg",
+    "diagnostic.info_fg",
+    "diagnostic.info_bg",
+    "diagnostic.hint_fg",
+    "diagnostic.hint_bg",
+    "syntax.keyword",
+    "syntax.string",
+    "syntax.comment",
+    "syntax.function",
+    "syntax.type",
+    "syntax.variable",
+    "syntax.constant",
+    "syntax.operator",
+    "diff.added_fg",
+    "diff.added_bg",
+    "diff.removed_fg",
+    "diff.removed_bg",
+    "diff.modified_fg",
+    "diff.modified_bg",
+];
+
+impl Theme {
+    /// Look up a color by name
+    ///
+    /// Accepts the paths colors have in theme files (`"diagnostic.error_fg"`),
+    /// the fields of this struct (`"diagnostic_error_fg"`) and the short
+    /// names in [`COLOR_ALIASES`] (`"diagnostic.error"`, `"accent"`).
+    pub fn named_color(&self, name: &str) -> Option<Color> {
+        let name = COLOR_ALIASES
+            .iter()
+            .find(|(alias, _)| *alias == name)
+            .map_or(name, |(_, path)| *path);
+        let color = match name {
+            "editor.bg" | "editor_bg" => self.editor_bg,
+            "editor.fg" | "editor_fg" => self.editor_fg,
+            "editor.cursor" | "cursor" => self.cursor,
+            "editor.inactive_cursor" | "inactive_cursor" => self.inactive_cursor,
+            "editor.selection_bg" | "selection_bg" => self.selection_bg,
+            "editor.current_line_bg" | "current_line_bg" => self.current_line_bg,
+            "editor.line_number_fg" | "line_number_fg" => self.line_number_fg,
+            "editor.line_number_bg" | "line_number_bg" => self.line_number_bg,
+            "editor.ruler_bg" | "ruler_bg" => self.ruler_bg,
+            "editor.overlength_bg" | "overlength_bg" => self.overlength_bg,
+            "ui.tab_active_fg" | "tab_active_fg" => self.tab_active_fg,
+            "ui.tab_active_bg" | "tab_active_bg" => self.tab_active_bg,
+            "ui.tab_inactive_fg" | "tab_inactive_fg" => self.tab_inactive_fg,
+            "ui.tab_inactive_bg" | "tab_inactive_bg" => self.tab_inactive_bg,
+            "ui.tab_separator_bg" | "tab_separator_bg" => self.tab_separator_bg,
+            "ui.tab_close_hover_fg" | "tab_close_hover_fg" => self.tab_close_hover_fg,
+            "ui.tab_hover_bg" | "tab_hover_bg" => self.tab_hover_bg,
+            "ui.menu_bg" | "menu_bg" => self.menu_bg,
+            "ui.menu_fg" | "menu_fg" => self.menu_fg,
+            "ui.menu_active_bg" | "menu_active_bg" => self.menu_active_bg,
+            "ui.menu_active_fg" | "menu_active_fg" => self.menu_active_fg,
+            "ui.menu_dropdown_bg" | "menu_dropdown_bg" => self.menu_dropdown_bg,
+            "ui.menu_dropdown_fg" | "menu_dropdown_fg" => self.menu_dropdown_fg,
+            "ui.menu_highlight_bg" | "menu_highlight_bg" => self.menu_highlight_bg,
+            "ui.menu_highlight_fg" | "menu_highlight_fg" => self.menu_highlight_fg,
+            "ui.menu_border_fg" | "menu_border_fg" => self.menu_border_fg,
+            "ui.menu_separator_fg" | "menu_separator_fg" => self.menu_separator_fg,
+            "ui.menu_hover_bg" | "menu_hover_bg" => self.menu_hover_bg,
+            "ui.menu_hover_fg" | "menu_hover_fg" => self.menu_hover_fg,
+            "ui.menu_disabled_fg" | "menu_disabled_fg" => self.menu_disabled_fg,
+            "ui.menu_disabled_bg" | "menu_disabled_bg" => self.menu_disabled_bg,
+            "ui.status_bar_fg" | "status_bar_fg" => self.status_bar_fg,
+            "ui.status_bar_bg" | "status_bar_bg" => self.status_bar_bg,
+            "ui.prompt_fg" | "prompt_fg" => self.prompt_fg,
+            "ui.prompt_bg" | "prompt_bg" => self.prompt_bg,
+            "ui.prompt_selection_fg" | "prompt_selection_fg" => self.prompt_selection_fg,
+            "ui.prompt_selection_bg" | "prompt_selection_bg" => self.prompt_selection_bg,
+            "ui.popup_border_fg" | "popup_border_fg" => self.popup_border_fg,
+            "ui.popup_bg" | "popup_bg" => self.popup_bg,
+            "ui.popup_selection_bg" | "popup_selection_bg" => self.popup_selection_bg,
+            "ui.popup_text_fg" | "popup_text_fg" => self.popup_text_fg,
+            "ui.suggestion_bg" | "suggestion_bg" => self.suggestion_bg,
+            "ui.suggestion_selected_bg" | "suggestion_selected_bg" => self.suggestion_selected_bg,
+            "ui.help_bg" | "help_bg" => self.help_bg,
+            "ui.help_fg" | "help_fg" => self.help_fg,
+            "ui.help_key_fg" | "help_key_fg" => self.help_key_fg,
+            "ui.help_separator_fg" | "help_separator_fg" => self.help_separator_fg,
+            "ui.help_indicator_fg" | "help_indicator_fg" => self.help_indicator_fg,
+            "ui.help_indicator_bg" | "help_indicator_bg" => self.help_indicator_bg,
+            "ui.inline_code_bg" | "inline_code_bg" => self.inline_code_bg,
+            "ui.split_separator_fg" | "split_separator_fg" => self.split_separator_fg,
+            "ui.split_separator_hover_fg" | "split_separator_hover_fg" => {
+                self.split_separator_hover_fg
+            }
+            "ui.scrollbar_track_fg" | "scrollbar_track_fg" => self.scrollbar_track_fg,
+            "ui.scrollbar_thumb_fg" | "scrollbar_thumb_fg" => self.scrollbar_thumb_fg,
+            "ui.scrollbar_track_hover_fg" | "scrollbar_track_hover_fg" => {
+                self.scrollbar_track_hover_fg
+            }
+            "ui.scrollbar_thumb_hover_fg" | "scrollbar_thumb_hover_fg" => {
+                self.scrollbar_thumb_hover_fg
+            }
+            "ui.compose_margin_bg" | "compose_margin_bg" => self.compose_margin_bg,
+            "ui.semantic_highlight_bg" | "semantic_highlight_bg" => self.semantic_highlight_bg,
+            "ui.terminal_bg" | "terminal_bg" => self.terminal_bg,
+            "ui.terminal_fg" | "terminal_fg" => self.terminal_fg,
+            "ui.status_warning_indicator_bg" | "status_warning_indicator_bg" => {
+                self.status_warning_indicator_bg
+            }
+            "ui.status_warning_indicator_fg" | "status_warning_indicator_fg" => {
+                self.status_warning_indicator_fg
+            }
+            "ui.status_error_indicator_bg" | "status_error_indicator_bg" => {
+                self.status_error_indicator_bg
+            }
+            "ui.status_error_indicator_fg" | "status_error_indicator_fg" => {
+                self.status_error_indicator_fg
+            }
+            "ui.status_warning_indicator_hover_bg" | "status_warning_indicator_hover_bg" => {
+                self.status_warning_indicator_hover_bg
+            }
+            "ui.status_warning_indicator_hover_fg" | "status_warning_indicator_hover_fg" => {
+                self.status_warning_indicator_hover_fg
+            }
+            "ui.status_error_indicator_hover_bg" | "status_error_indicator_hover_bg" => {
+                self.status_error_indicator_hover_bg
+            }
+            "ui.status_error_indicator_hover_fg" | "status_error_indicator_hover_fg" => {
+                self.status_error_indicator_hover_fg
+            }
+            "ui.tab_drop_zone_bg" | "tab_drop_zone_bg" => self.tab_drop_zone_bg,
+            "ui.tab_drop_zone_border" | "tab_drop_zone_border" => self.tab_drop_zone_border,
+            "search.match_bg" | "search_match_bg" => self.search_match_bg,
+            "search.match_fg" | "search_match_fg" => self.search_match_fg,
+            "diagnostic.error_fg" | "diagnostic_error_fg" => self.diagnostic_error_fg,
+            "diagnostic.error_bg" | "diagnostic_error_bg" => self.diagnostic_error_bg,
+            "diagnostic.warning_fg" | "diagnostic_warning_fg" => self.diagnostic_warning_fg,
+            "diagnostic.warning_bg" | "diagnostic_warning_bg" => self.diagnostic_warning_bg,
+            "diagnostic.info_fg" | "diagnostic_info_fg" => self.diagnostic_info_fg,
+            "diagnostic.info_bg" | "diagnostic_info_bg" => self.diagnostic_info_bg,
+            "diagnostic.hint_fg" | "diagnostic_hint_fg" => self.diagnostic_hint_fg,
+            "diagnostic.hint_bg" | "diagnostic_hint_bg" => self.diagnostic_hint_bg,
+            "syntax.keyword" | "syntax_keyword" => self.syntax_keyword,
+            "syntax.string" | "syntax_string" => self.syntax_string,
+            "syntax.comment" | "syntax_comment" => self.syntax_comment,
+            "syntax.function" | "syntax_function" => self.syntax_function,
+            "syntax.type" | "syntax_type" => self.syntax_type,
+            "syntax.variable" | "syntax_variable" => self.syntax_variable,
+            "syntax.constant" | "syntax_constant" => self.syntax_constant,
+            "syntax.operator" | "syntax_operator" => self.syntax_operator,
+            "diff.added_fg" | "diff_added_fg" => self.diff_added_fg,
+            "diff.added_bg" | "diff_added_bg" => self.diff_added_bg,
+            "diff.removed_fg" | "diff_removed_fg" => self.diff_removed_fg,
+            "diff.removed_bg" | "diff_removed_bg" => self.diff_removed_bg,
+            "diff.modified_fg" | "diff_modified_fg" => self.diff_modified_fg,
+            "diff.modified_bg" | "diff_modified_bg" => self.diff_modified_bg,
+            _ => return None,
+        };
+        Some(color)
+    }
+
+    /// Every color [`Theme::named_color`] knows, by alias and theme file path
+    pub fn named_colors(&self) -> Vec<(&'static str, Color)> {
+        COLOR_ALIASES
+            .iter()
+            .map(|(alias, _)| *alias)
+            .chain(THEME_COLOR_PATHS.iter().copied())
+            .filter_map(|name| Some((name, self.named_color(name)?)))
+            .collect()
+    }
+}
+
+/// Color of a plugin decoration
+///
+/// Named colors are looked up in the theme each time the decoration is
+/// drawn, so they follow theme changes. Serializes like an `(r, g, b)`
+/// tuple when it is one.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum DecorationColor {
+    Rgb(u8, u8, u8),
+    /// Name accepted by [`Theme::named_color`]
+    Named(String),
+}
+
+impl DecorationColor {
+    /// The color to draw with in `theme`, or `fallback` for an unknown name
+    pub fn resolve(&self, theme: &Theme, fallback: Color) -> Color {
+        match self {
+            Self::Rgb(r, g, b) => Color::Rgb(*r, *g, *b),
+            Self::Named(name) => theme.named_color(name).unwrap_or_else(|| {
+                warn_unknown_color(name);
+                fallback
+            }),
+        }
+    }
+
+    /// The fixed color, if this isn't a theme color
+    pub fn rgb(&self) -> Option<Color> {
+        match self {
+            Self::Rgb(r, g, b) => Some(Color::Rgb(*r, *g, *b)),
+            Self::Named(_) => None,
+        }
+    }
+}
+
+impl From<(u8, u8, u8)> for DecorationColor {
+    fn from((r, g, b): (u8, u8, u8)) -> Self {
+        Self::Rgb(r, g, b)
+    }
+}
+
+/// Log an unknown decoration color name, once per name
+fn warn_unknown_color(name: &str) {
+    static WARNED: OnceLock<Mutex<HashSet<String>>> = OnceLock::new();
+    let mut warned = WARNED
+        .get_or_init(Default::default)
+        .lock()
+        .unwrap_or_else(|e| e.into_inner());
+    if warned.insert(name.to_string()) {
+        tracing::warn!("Unknown theme color '{}' in a decoration", name);
+    }
+}
+
+/// Text style whose colors may be theme colors
+#[derive(Debug, Clone, PartialEq)]
+pub struct ThemedStyle {
+    pub fg: Option<DecorationColor>,
+    pub bg: Option<DecorationColor>,
+    pub add_modifier: Modifier,
+}
+
+impl ThemedStyle {
+    /// The style to draw with in `theme`
+    ///
+    /// Unknown foreground names fall back to the editor text color; unknown
+    /// backgrounds are left out.
+    pub fn resolve(&self, theme: &Theme) -> Style {
+        let mut style = Style::default().add_modifier(self.add_modifier);
+        if let Some(fg) = &self.fg {
+            style = style.fg(fg.resolve(theme, theme.editor_fg));
+        }
+        if let Some(bg) = &self.bg {
+            let bg = bg.resolve(theme, Color::Reset);
+            if bg != Color::Reset {
+                style = style.bg(bg);
+            }
+        }
+        style
+    }
+
+    /// The style, if it uses no theme colors and so never changes
+    pub fn fixed(&self) -> Option<Style> {
+        let mut style = Style::default().add_modifier(self.add_modifier);
+        if let Some(fg) = &self.fg {
+            style = style.fg(fg.rgb()?);
+        }
+        if let Some(bg) = &self.bg {
+            style = style.bg(bg.rgb()?);
+        }
+        Some(style)
+    }
+}
+
+/// Contrast ratio WCAG asks of normal text (level AA)
+pub const TEXT_CONTRAST: f64 = 4.5;
+
+/// Contrast ratio WCAG asks of cursors, borders and other marks that
+/// aren't text
+pub const NON_TEXT_CONTRAST: f64 = 3.0;
+
+/// Contrast ratio the text of high-contrast variants is raised to (level AAA)
+const HIGH_TEXT_CONTRAST: f64 = 7.0;
+
+/// Suffix of the names of generated high-contrast variants
+/// (`"dark-high-contrast"`)
+pub const HIGH_CONTRAST_SUFFIX: &str = "-high-contrast";
+
+/// Foreground/background pairs the renderer draws, with the contrast each
+/// needs, as theme file paths
+const CONTRAST_PAIRS: &[(&str, &str, f64)] = &[
+    ("editor.fg", "editor.bg", TEXT_CONTRAST),
+    ("editor.fg", "editor.selection_bg", TEXT_CONTRAST),
+    ("editor.fg", "editor.current_line_bg", TEXT_CONTRAST),
+    (
+        "editor.line_number_fg",
+        "editor.line_number_bg",
+        TEXT_CONTRAST,
+    ),
+    ("editor.cursor", "editor.bg", NON_TEXT_CONTRAST),
+    ("editor.cursor", "editor.selection_bg", NON_TEXT_CONTRAST),
+    ("syntax.keyword", "editor.bg", TEXT_CONTRAST),
+    ("syntax.string", "editor.bg", TEXT_CONTRAST),
+    ("syntax.comment", "editor.bg", TEXT_CONTRAST),
+    ("syntax.function", "editor.bg", TEXT_CONTRAST),
+    ("syntax.type", "editor.bg", TEXT_CONTRAST),
+    ("syntax.variable", "editor.bg", TEXT_CONTRAST),
+    ("syntax.constant", "editor.bg", TEXT_CONTRAST),
+    ("syntax.operator", "editor.bg", TEXT_CONTRAST),
+    ("diagnostic.error_fg", "editor.bg", TEXT_CONTRAST),
+    ("diagnostic.warning_fg", "editor.bg", TEXT_CONTRAST),
+    ("diagnostic.info_fg", "editor.bg", TEXT_CONTRAST),
+    ("diagnostic.hint_fg", "editor.bg", TEXT_CONTRAST),
+    ("search.match_fg", "search.match_bg", TEXT_CONTRAST),
+    ("ui.status_bar_fg", "ui.status_bar_bg", TEXT_CONTRAST),
+    ("ui.prompt_fg", "ui.prompt_bg", TEXT_CONTRAST),
+    (
+        "ui.prompt_selection_fg",
+        "ui.prompt_selection_bg",
+        TEXT_CONTRAST,
+    ),
+    ("ui.popup_text_fg", "ui.popup_bg", TEXT_CONTRAST),
+    ("ui.popup_text_fg", "ui.popup_selection_bg", TEXT_CONTRAST),
+    ("ui.popup_border_fg", "ui.popup_bg", NON_TEXT_CONTRAST),
+    ("ui.tab_active_fg", "ui.tab_active_bg", TEXT_CONTRAST),
+    ("ui.tab_inactive_fg", "ui.tab_inactive_bg", TEXT_CONTRAST),
+    ("ui.menu_fg", "ui.menu_bg", TEXT_CONTRAST),
+    ("ui.menu_dropdown_fg", "ui.menu_dropdown_bg", TEXT_CONTRAST),
+    (
+        "ui.menu_highlight_fg",
+        "ui.menu_highlight_bg",
+        TEXT_CONTRAST,
+    ),
+    ("ui.help_fg", "ui.help_bg", TEXT_CONTRAST),
+    ("ui.help_key_fg", "ui.help_bg", TEXT_CONTRAST),
+];
+
+/// Most color pairs a [`ContrastCache`] keeps before starting over
+const CONTRAST_CACHE_ENTRIES: usize = 4096;
+
+/// How a foreground/background pair of a theme measures up
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContrastCheck {
+    /// Theme file path of the foreground
+    pub foreground: &'static str,
+    /// Theme file path of the background
+    pub background: &'static str,
+    pub ratio: f64,
+    pub required: f64,
+    /// Nearest foreground of the same hue with the required contrast, for
+    /// pairs that fall short
+    pub suggestion: Option<Color>,
+}
+
+impl ContrastCheck {
+    pub fn passes(&self) -> bool {
+        self.ratio >= self.required
+    }
+}
+
+impl Theme {
+    /// Contrast of the color pairs the renderer relies on
+    ///
+    /// Pairs with a terminal color are left out: what it looks like isn't
+    /// known.
+    pub fn contrast_checks(&self) -> Vec<ContrastCheck> {
+        CONTRAST_PAIRS
+            .iter()
+            .filter_map(|&(foreground, background, required)| {
+                let fg = self.named_color(foreground)?;
+                let bg = self.named_color(background)?;
+                let ratio = contrast_ratio(fg, bg)?;
+                Some(ContrastCheck {
+                    foreground,
+                    background,
+                    ratio,
+                    required,
+                    suggestion: (ratio < required).then(|| adjust_for_contrast(fg, bg, required)),
+                })
+            })
+            .collect()
+    }
+
+    /// This theme with its foregrounds lightened or darkened until text
+    /// reaches [`HIGH_TEXT_CONTRAST`] and other marks [`TEXT_CONTRAST`]
+    ///
+    /// Backgrounds are kept, so the variant looks like the theme it comes
+    /// from.
+    pub fn high_contrast_variant(&self) -> Self {
+        let mut theme = self.clone();
+        theme.name = format!("{}{}", self.name, HIGH_CONTRAST_SUFFIX);
+        // A foreground drawn on several backgrounds may need another pass
+        // once it was adjusted for a later one
+        for _ in 0..3 {
+            let mut changed = false;
+            for &(foreground, background, required) in CONTRAST_PAIRS {
+                let minimum = if required >= TEXT_CONTRAST {
+                    HIGH_TEXT_CONTRAST
+                } else {
+                    TEXT_CONTRAST
+                };
+                let Some(bg) = theme.named_color(background) else {
+                    continue;
+                };
+                let Some(fg) = theme.foreground_mut(foreground) else {
+                    continue;
+                };
+                let adjusted = adjust_for_contrast(*fg, bg, minimum);
+                if adjusted != *fg {
+                    *fg = adjusted;
+                    changed = true;
+                }
+            }
+            if !changed {
+                break;
+            }
+        }
+        theme
+    }
+
+    /// The foreground colors of [`CONTRAST_PAIRS`], by theme file path
+    fn foreground_mut(&mut self, path: &str) -> Option<&mut Color> {
+        let color = match path {
+            "editor.fg" => &mut self.editor_fg,
+            "editor.line_number_fg" => &mut self.line_number_fg,
+            "editor.cursor" => &mut self.cursor,
+            "syntax.keyword" => &mut self.syntax_keyword,
+            "syntax.string" => &mut self.syntax_string,
+            "syntax.comment" => &mut self.syntax_comment,
+            "syntax.function" => &mut self.syntax_function,
+            "syntax.type" => &mut self.syntax_type,
+            "syntax.variable" => &mut self.syntax_variable,
+            "syntax.constant" => &mut self.syntax_constant,
+            "syntax.operator" => &mut self.syntax_operator,
+            "diagnostic.error_fg" => &mut self.diagnostic_error_fg,
+            "diagnostic.warning_fg" => &mut self.diagnostic_warning_fg,
+            "diagnostic.info_fg" => &mut self.diagnostic_info_fg,
+            "diagnostic.hint_fg" => &mut self.diagnostic_hint_fg,
+            "search.match_fg" => &mut self.search_match_fg,
+            "ui.status_bar_fg" => &mut self.status_bar_fg,
+            "ui.prompt_fg" => &mut self.prompt_fg,
+            "ui.prompt_selection_fg" => &mut self.prompt_selection_fg,
+            "ui.popup_text_fg" => &mut self.popup_text_fg,
+            "ui.popup_border_fg" => &mut self.popup_border_fg,
+            "ui.tab_active_fg" => &mut self.tab_active_fg,
+            "ui.tab_inactive_fg" => &mut self.tab_inactive_fg,
+            "ui.menu_fg" => &mut self.menu_fg,
+            "ui.menu_dropdown_fg" => &mut self.menu_dropdown_fg,
+            "ui.menu_highlight_fg" => &mut self.menu_highlight_fg,
+            "ui.help_fg" => &mut self.help_fg,
+            "ui.help_key_fg" => &mut self.help_key_fg,
+            _ => return None,
+        };
+        Some(color)
+    }
+}
+
+/// sRGB components of a color, None for the terminal's default colors
+///
+/// Named and indexed colors use xterm's default palette.
+pub fn color_rgb(color: Color) -> Option<(u8, u8, u8)> {
+    const ANSI: [(u8, u8, u8); 16] = [
+        (0, 0, 0),
+        (205, 0, 0),
+        (0, 205, 0),
+        (205, 205, 0),
+        (0, 0, 238),
+        (205, 0, 205),
+        (0, 205, 205),
+        (229, 229, 229),
+        (127, 127, 127),
+        (255, 0, 0),
+        (0, 255, 0),
+        (255, 255, 0),
+        (92, 92, 255),
+        (255, 0, 255),
+        (0, 255, 255),
+        (255, 255, 255),
+    ];
+    let index = match color {
+        Color::Reset => return None,
+        Color::Rgb(r, g, b) => return Some((r, g, b)),
+        Color::Indexed(idx @ 16..=231) => {
+            let level = |i: u8| if i == 0 { 0 } else { 55 + 40 * i };
+            let idx = idx - 16;
+            return Some((level(idx / 36), level(idx / 6 % 6), level(idx % 6)));
+        }
+        Color::Indexed(idx @ 232..=255) => {
+            let gray = 8 + 10 * (idx - 232);
+            return Some((gray, gray, gray));
+        }
+        Color::Indexed(idx) => idx,
+        Color::Black => 0,
+        Color::Red => 1,
+        Color::Green => 2,
+        Color::Yellow => 3,
+        Color::Blue => 4,
+        Color::Magenta => 5,
+        Color::Cyan => 6,
+        Color::Gray => 7,
+        Color::DarkGray => 8,
+        Color::LightRed => 9,
+        Color::LightGreen => 10,
+        Color::LightYellow => 11,
+        Color::LightBlue => 12,
+        Color::LightMagenta => 13,
+        Color::LightCyan => 14,
+        Color::White => 15,
+    };
+    Some(ANSI[index as usize])
+}
+
+/// WCAG relative luminance of sRGB components, from 0 (black) to 1 (white)
+fn luminance((r, g, b): (u8, u8, u8)) -> f64 {
+    let linear = |c: u8| {
+        let c = c as f64 / 255.0;
+        if c <= 0.04045 {
+            c / 12.92
+        } else {
+            ((c + 0.055) / 1.055).powf(2.4)
+        }
+    };
+    0.2126 * linear(r) + 0.7152 * linear(g) + 0.0722 * linear(b)
+}
+
+/// WCAG relative luminance of a color, None for terminal default colors
+pub fn relative_luminance(color: Color) -> Option<f64> {
+    color_rgb(color).map(luminance)
+}
+
+/// Ratio of the luminances of two colors, lighter over darker, from 1 (no
+/// contrast) to 21 (black on white)
+pub fn contrast_ratio(fg: Color, bg: Color) -> Option<f64> {
+    let fg = relative_luminance(fg)?;
+    let bg = relative_luminance(bg)?;
+    Some((fg.max(bg) + 0.05) / (fg.min(bg) + 0.05))
+}
+
+/// Luminance above which a background is light: black text on it contrasts
+/// more than white text
+const LIGHT_BACKGROUND_LUMINANCE: f64 = 0.179;
+
+/// Whether a background is light or dark
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Appearance {
+    Light,
+    Dark,
+}
+
+impl Appearance {
+    /// Appearance of a background color, None for terminal default colors
+    pub fn of_background(color: Color) -> Option<Self> {
+        relative_luminance(color).map(|luminance| {
+            if luminance > LIGHT_BACKGROUND_LUMINANCE {
+                Self::Light
+            } else {
+                Self::Dark
+            }
+        })
+    }
+
+    /// The other appearance
+    pub fn toggled(self) -> Self {
+        match self {
+            Self::Light => Self::Dark,
+            Self::Dark => Self::Light,
+        }
+    }
+}
+
+/// Hue (0-360), saturation and lightness (0-1) of sRGB components
+fn rgb_to_hsl((r, g, b): (u8, u8, u8)) -> (f64, f64, f64) {
+    let (r, g, b) = (r as f64 / 255.0, g as f64 / 255.0, b as f64 / 255.0);
+    let max = r.max(g).max(b);
+    let min = r.min(g).min(b);
+    let lightness = (max + min) / 2.0;
+    let delta = max - min;
+    if delta == 0.0 {
+        return (0.0, 0.0, lightness);
+    }
+    let saturation = delta / (1.0 - (2.0 * lightness - 1.0).abs());
+    let hue = if max == r {
+        60.0 * ((g - b) / delta).rem_euclid(6.0)
+    } else if max == g {
+        60.0 * ((b - r) / delta + 2.0)
+    } else {
+        60.0 * ((r - g) / delta + 4.0)
+    };
+    (hue, saturation, lightness)
+}
+
+/// sRGB components of a hue, saturation and lightness
+fn hsl_to_rgb(hue: f64, saturation: f64, lightness: f64) -> (u8, u8, u8) {
+    let chroma = (1.0 - (2.0 * lightness - 1.0).abs()) * saturation;
+    let x = chroma * (1.0 - ((hue / 60.0).rem_euclid(2.0) - 1.0).abs());
+    let (r, g, b) = match (hue / 60.0) as u32 {
+        0 => (chroma, x, 0.0),
+        1 => (x, chroma, 0.0),
+        2 => (0.0, chroma, x),
+        3 => (0.0, x, chroma),
+        4 => (x, 0.0, chroma),
+        _ => (chroma, 0.0, x),
+    };
+    let m = lightness - chroma / 2.0;
+    let channel = |c: f64| ((c + m) * 255.0).round().clamp(0.0, 255.0) as u8;
+    (channel(r), channel(g), channel(b))
+}
+
+/// `fg`, lightened or darkened just enough to have `minimum` contrast
+/// against `bg`
+///
+/// Hue and saturation are kept, and of the two directions the one needing
+/// the smaller change in lightness wins. Colors that already have the
+/// contrast come back as they are, as do terminal default colors; when no
+/// lightness gets there, black or white, whichever has more contrast.
+pub fn adjust_for_contrast(fg: Color, bg: Color, minimum: f64) -> Color {
+    let (Some(fg_rgb), Some(bg_rgb)) = (color_rgb(fg), color_rgb(bg)) else {
+        return fg;
+    };
+    let bg_luminance = luminance(bg_rgb);
+    let ratio = |rgb: (u8, u8, u8)| {
+        let l = luminance(rgb);
+        (l.max(bg_luminance) + 0.05) / (l.min(bg_luminance) + 0.05)
+    };
+    if ratio(fg_rgb) >= minimum {
+        return fg;
+    }
+
+    let (hue, saturation, lightness) = rgb_to_hsl(fg_rgb);
+    let at = |l: f64| hsl_to_rgb(hue, saturation, l);
+    // Lightness only ever helps past the background's luminance, and from
+    // there on contrast grows with the distance, so each direction is a
+    // bisection between the color and black or white
+    let search = |mut passing: f64, mut failing: f64| {
+        for _ in 0..24 {
+            let mid = (passing + failing) / 2.0;
+            if ratio(at(mid)) >= minimum {
+                passing = mid;
+            } else {
+                failing = mid;
+            }
+        }
+        passing
+    };
+    let lighter = (ratio(at(1.0)) >= minimum).then(|| search(1.0, lightness));
+    let darker = (ratio(at(0.0)) >= minimum).then(|| search(0.0, lightness));
+    let target = match (lighter, darker) {
+        (Some(up), Some(down)) if up - lightness <= lightness - down => up,
+        (_, Some(down)) => down,
+        (Some(up), None) => up,
+        (None, None) if ratio(at(1.0)) >= ratio(at(0.0)) => 1.0,
+        (None, None) => 0.0,
+    };
+    let (r, g, b) = at(target);
+    Color::Rgb(r, g, b)
+}
+
+/// Text colors adjusted for `ui.minimum_contrast`, by foreground and
+/// background
+///
+/// Text is drawn in a handful of color pairs, so each pair is adjusted
+/// once instead of for every character of every frame.
+#[derive(Debug, Default)]
+pub struct ContrastCache {
+    minimum: Option<f64>,
+    adjusted: HashMap<(Color, Color), Color>,
+    /// The pair asked for last, as neighboring characters mostly share one
+    last: Option<((Color, Color), Color)>,
+}
+
+impl ContrastCache {
+    pub fn new(minimum: Option<f64>) -> Self {
+        Self {
+            minimum,
+            adjusted: HashMap::new(),
+            last: None,
+        }
+    }
+
+    /// Follow a change of the minimum contrast, None to adjust nothing
+    pub fn set_minimum(&mut self, minimum: Option<f64>) {
+        if self.minimum != minimum {
+            self.minimum = minimum;
+            self.adjusted.clear();
+            self.last = None;
+        }
+    }
+
+    /// `fg` as text on `bg` is drawn: adjusted with [`adjust_for_contrast`]
+    /// when a minimum is set
+    pub fn foreground(&mut self, fg: Color, bg: Color) -> Color {
+        let Some(minimum) = self.minimum else {
+            return fg;
+        };
+        if let Some((pair, adjusted)) = self.last {
+            if pair == (fg, bg) {
+                return adjusted;
+            }
+        }
+        if self.adjusted.len() >= CONTRAST_CACHE_ENTRIES {
+            self.adjusted.clear();
+        }
+        let adjusted = *self
+            .adjusted
+            .entry((fg, bg))
+            .or_insert_with(|| adjust_for_contrast(fg, bg, minimum));
+        self.last = Some(((fg, bg), adjusted));
+        adjusted
+    }
+
+    /// Color pairs adjusted so far
+    pub fn len(&self) -> usize {
+        self.adjusted.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.adjusted.is_empty()
+    }
+}
+
 impl Default for Theme {
     fn default() -> Self {
         Self::high_contrast()
@@ -1167,6 +2296,24 @@ mod tests {
         assert_eq!(theme.name, "high-contrast");
     }
 
+    #[test]
+    fn test_theme_file_whitespace_fallbacks() {
+        let mut value: serde_json::Value =
+            serde_json::from_str(include_str!("../../themes/dracula.json")).unwrap();
+        value["whitespace"] = serde_json::json!({
+            "space": { "symbol": "•" },
+            "trailing_bg": [80, 0, 0],
+        });
+        let file: ThemeFile = serde_json::from_value(value).unwrap();
+        let theme = Theme::from(file);
+
+        assert_eq!(theme.whitespace.space.symbol, '•');
+        assert_eq!(theme.whitespace.space.fg, theme.line_number_fg);
+        assert_eq!(theme.whitespace.tab.symbol, '→');
+        assert_eq!(theme.whitespace.trailing_bg, Color::Rgb(80, 0, 0));
+        assert_eq!(theme.whitespace.control_fg, theme.diagnostic_warning_fg);
+    }
+
     #[test]
     fn test_default_reset_color() {
         // Test that "Default" maps to Color::Reset
@@ -1177,4 +2324,203 @@ mod tests {
         let color: Color = ColorDef::Named("Reset".to_string()).into();
         assert_eq!(color, Color::Reset);
     }
+
+    #[test]
+    fn test_terminal_color() {
+        // null and "terminal" leave the color to the terminal
+        let def: ColorDef = serde_json::from_str("null").unwrap();
+        assert_eq!(Color::from(def), Color::Reset);
+        let def: ColorDef = serde_json::from_str("\"terminal\"").unwrap();
+        assert_eq!(Color::from(def), Color::Reset);
+        let def: ColorDef = serde_json::from_str("[1, 2, 3]").unwrap();
+        assert_eq!(Color::from(def), Color::Rgb(1, 2, 3));
+    }
+
+    #[test]
+    fn test_named_colors() {
+        let theme = Theme::dark();
+        assert_eq!(
+            theme.named_color("diagnostic.error"),
+            Some(theme.diagnostic_error_fg)
+        );
+        assert_eq!(theme.named_color("accent"), Some(theme.cursor));
+        assert_eq!(theme.named_color("syntax.type"), Some(theme.syntax_type));
+        assert_eq!(
+            theme.named_color("diff_added_bg"),
+            Some(theme.diff_added_bg)
+        );
+        assert_eq!(theme.named_color("no.such_color"), None);
+        assert!(theme
+            .named_colors()
+            .iter()
+            .any(|(name, _)| *name == "search.match"));
+    }
+
+    #[test]
+    fn test_decoration_color_serializes_like_rgb_tuple() {
+        let rgb = serde_json::to_string(&DecorationColor::Rgb(1, 2, 3)).unwrap();
+        assert_eq!(rgb, serde_json::to_string(&(1u8, 2u8, 3u8)).unwrap());
+        let named: DecorationColor = serde_json::from_str("\"diff.added\"").unwrap();
+        assert_eq!(named, DecorationColor::Named("diff.added".to_string()));
+        assert_eq!(
+            named.resolve(&Theme::light(), Color::Reset),
+            Theme::light().diff_added_fg
+        );
+    }
+
+    #[test]
+    fn test_contrast_ratio_of_known_pairs() {
+        let ratio = |fg, bg| contrast_ratio(fg, bg).unwrap();
+        assert!((ratio(Color::Rgb(0, 0, 0), Color::Rgb(255, 255, 255)) - 21.0).abs() < 1e-9);
+        assert!((ratio(Color::Rgb(255, 255, 255), Color::Rgb(255, 255, 255)) - 1.0).abs() < 1e-9);
+        // The order of the colors doesn't matter
+        assert_eq!(
+            ratio(Color::Rgb(119, 119, 119), Color::White),
+            ratio(Color::White, Color::Rgb(119, 119, 119))
+        );
+        // #777 on white is the classic just-failing gray, #767676 just passes
+        assert!((ratio(Color::Rgb(119, 119, 119), Color::White) - 4.48).abs() < 0.01);
+        assert!((ratio(Color::Rgb(118, 118, 118), Color::White) - 4.54).abs() < 0.01);
+        assert!((ratio(Color::Rgb(255, 0, 0), Color::Rgb(255, 255, 255)) - 4.0).abs() < 0.01);
+        assert!((ratio(Color::Rgb(0, 0, 255), Color::Rgb(0, 0, 0)) - 2.44).abs() < 0.01);
+        assert_eq!(contrast_ratio(Color::Reset, Color::Black), None);
+    }
+
+    #[test]
+    fn test_appearance_of_background() {
+        assert_eq!(
+            Appearance::of_background(Theme::light().editor_bg),
+            Some(Appearance::Light)
+        );
+        assert_eq!(
+            Appearance::of_background(Theme::dark().editor_bg),
+            Some(Appearance::Dark)
+        );
+        // Middle gray is on the light side, a shade darker on the dark one
+        assert_eq!(
+            Appearance::of_background(Color::Rgb(128, 128, 128)),
+            Some(Appearance::Light)
+        );
+        assert_eq!(
+            Appearance::of_background(Color::Rgb(112, 112, 112)),
+            Some(Appearance::Dark)
+        );
+        assert_eq!(Appearance::of_background(Color::Reset), None);
+    }
+
+    #[test]
+    fn test_color_rgb_of_palette_colors() {
+        assert_eq!(color_rgb(Color::White), Some((255, 255, 255)));
+        assert_eq!(color_rgb(Color::Indexed(1)), color_rgb(Color::Red));
+        assert_eq!(color_rgb(Color::Indexed(16)), Some((0, 0, 0)));
+        assert_eq!(color_rgb(Color::Indexed(231)), Some((255, 255, 255)));
+        assert_eq!(color_rgb(Color::Indexed(196)), Some((255, 0, 0)));
+        assert_eq!(color_rgb(Color::Indexed(244)), Some((128, 128, 128)));
+        assert_eq!(color_rgb(Color::Reset), None);
+    }
+
+    #[test]
+    fn test_adjust_for_contrast_keeps_hue_and_reaches_minimum() {
+        // Dim gray on dark gray gets lighter
+        let bg = Color::Rgb(40, 40, 40);
+        let adjusted = adjust_for_contrast(Color::Rgb(90, 90, 90), bg, TEXT_CONTRAST);
+        let Color::Rgb(r, g, b) = adjusted else {
+            panic!("expected an RGB color, got {:?}", adjusted);
+        };
+        assert!(r == g && g == b && r > 90, "{:?}", adjusted);
+        assert!(contrast_ratio(adjusted, bg).unwrap() >= TEXT_CONTRAST);
+        // Just enough: one step darker falls short
+        assert!(contrast_ratio(Color::Rgb(r - 2, g - 2, b - 2), bg).unwrap() < TEXT_CONTRAST);
+
+        // Light blue on white gets darker and stays blue
+        let bg = Color::White;
+        let adjusted = adjust_for_contrast(Color::Rgb(120, 160, 255), bg, TEXT_CONTRAST);
+        let Color::Rgb(r, g, b) = adjusted else {
+            panic!("expected an RGB color, got {:?}", adjusted);
+        };
+        assert!(b > g && g > r, "{:?}", adjusted);
+        assert!(contrast_ratio(adjusted, bg).unwrap() >= TEXT_CONTRAST);
+
+        // Colors that pass, and terminal colors, are left alone
+        assert_eq!(
+            adjust_for_contrast(Color::White, Color::Black, TEXT_CONTRAST),
+            Color::White
+        );
+        assert_eq!(
+            adjust_for_contrast(Color::Reset, Color::Black, TEXT_CONTRAST),
+            Color::Reset
+        );
+        // Nothing reaches 21:1 on mid gray: the better extreme is used
+        assert_eq!(
+            adjust_for_contrast(Color::Rgb(150, 150, 150), Color::Rgb(128, 128, 128), 21.0),
+            Color::Rgb(0, 0, 0)
+        );
+    }
+
+    #[test]
+    fn test_contrast_checks_report_failing_pairs_with_suggestion() {
+        let mut theme = Theme::dark();
+        theme.diagnostic_hint_fg = Color::Rgb(60, 60, 60);
+        let checks = theme.contrast_checks();
+        let hint = checks
+            .iter()
+            .find(|c| c.foreground == "diagnostic.hint_fg")
+            .unwrap();
+        assert!(!hint.passes());
+        let suggestion = hint.suggestion.unwrap();
+        assert!(contrast_ratio(suggestion, theme.editor_bg).unwrap() >= TEXT_CONTRAST);
+        let text = checks
+            .iter()
+            .find(|c| c.foreground == "editor.fg" && c.background == "editor.bg")
+            .unwrap();
+        assert!(text.passes());
+        assert_eq!(text.suggestion, None);
+
+        // Pairs drawn on the terminal's background aren't checked
+        theme.make_background_transparent();
+        assert!(!theme
+            .contrast_checks()
+            .iter()
+            .any(|c| c.background == "editor.bg"));
+    }
+
+    #[test]
+    fn test_high_contrast_variant_passes_every_check() {
+        for base in [Theme::dark(), Theme::light()] {
+            let variant = base.high_contrast_variant();
+            assert_eq!(variant.name, format!("{}-high-contrast", base.name));
+            assert_eq!(variant.editor_bg, base.editor_bg);
+            for check in variant.contrast_checks() {
+                assert!(
+                    check.passes(),
+                    "{}: {} on {} is {:.2}",
+                    variant.name,
+                    check.foreground,
+                    check.background,
+                    check.ratio
+                );
+            }
+        }
+        assert_eq!(
+            Theme::from_name("light-high-contrast").name,
+            "light-high-contrast"
+        );
+    }
+
+    #[test]
+    fn test_contrast_cache_adjusts_each_pair_once() {
+        let mut cache = ContrastCache::new(None);
+        let (fg, bg) = (Color::Rgb(90, 90, 90), Color::Rgb(40, 40, 40));
+        assert_eq!(cache.foreground(fg, bg), fg);
+        assert!(cache.is_empty());
+
+        cache.set_minimum(Some(TEXT_CONTRAST));
+        let adjusted = cache.foreground(fg, bg);
+        assert_eq!(adjusted, adjust_for_contrast(fg, bg, TEXT_CONTRAST));
+        assert_eq!(cache.foreground(fg, bg), adjusted);
+        assert_eq!(cache.len(), 1);
+
+        cache.set_minimum(Some(7.0));
+        assert!(cache.is_empty());
+    }
 }
@@ -12,6 +12,7 @@ use crate::app::file_open::{
     format_modified, format_size, FileOpenSection, FileOpenState, SortMode,
 };
 use crate::primitives::display_width::str_width;
+use crate::view::glyphs::glyphs;
 use ratatui::layout::Rect;
 use ratatui::style::{Modifier, Style};
 use ratatui::text::{Line, Span};
@@ -77,6 +78,7 @@ impl FileBrowserRenderer {
         // Create the popup block with border
         let block = Block::default()
             .borders(Borders::ALL)
+            .border_set(glyphs().border_set())
             .border_style(Style::default().fg(theme.popup_border_fg))
             .style(Style::default().bg(theme.popup_bg))
             .title(title_line);
@@ -177,7 +179,11 @@ impl FileBrowserRenderer {
             .unwrap_or_default();
 
         // First line: "Show Hidden" checkbox (on its own row to avoid truncation on Windows)
-        let checkbox_icon = if state.show_hidden { "☑" } else { "☐" };
+        let checkbox_icon = if state.show_hidden {
+            glyphs().checkbox_on
+        } else {
+            glyphs().checkbox_off
+        };
         let checkbox_label = format!("{} {}", checkbox_icon, t!("file_browser.show_hidden"));
         let shortcut_text = if shortcut_hint.is_empty() {
             String::new()
@@ -259,7 +265,7 @@ impl FileBrowserRenderer {
 
             if idx < state.shortcuts.len() - 1 {
                 nav_spans.push(Span::styled(
-                    " │ ",
+                    format!(" {} ", glyphs().border_vertical),
                     Style::default()
                         .fg(theme.help_separator_fg)
                         .bg(theme.popup_bg),
@@ -315,7 +321,11 @@ impl FileBrowserRenderer {
             .add_modifier(Modifier::BOLD);
 
         // Sort indicator
-        let sort_arrow = if state.sort_ascending { "▲" } else { "▼" };
+        let sort_arrow = if state.sort_ascending {
+            glyphs().arrow_up
+        } else {
+            glyphs().arrow_down
+        };
 
         let mut spans = Vec::new();
 
@@ -1,16 +1,100 @@
+use crate::input::keybindings::Action;
+use crate::primitives::display_text::{sanitize_line, MAX_LABEL_CHARS};
 use crate::primitives::display_width::str_width;
-use crate::view::file_tree::{FileTreeView, NodeId};
+use crate::primitives::relative_time::format_relative_time;
+use crate::primitives::truncate::truncate_end;
+use crate::view::file_tree::{FileTreeView, NodeId, SortMode, TreeRow};
+use crate::view::glyphs::glyphs;
 use crate::view::theme::Theme;
 use ratatui::{
     layout::Rect,
-    style::{Modifier, Style},
+    style::{Color, Modifier, Style},
     text::{Line, Span},
     widgets::{Block, Borders, List, ListItem, ListState},
     Frame,
 };
 
+use rust_i18n::t;
 use std::collections::HashSet;
 use std::path::PathBuf;
+use std::time::SystemTime;
+
+/// Names are never squeezed below this many columns to make room for the
+/// details columns
+const MIN_NAME_WIDTH: usize = 12;
+
+/// Width of the expansion and modified markers before a name
+const INDICATOR_WIDTH: usize = 2;
+
+/// Layout of the rows in details mode, fitted to the explorer's width
+///
+/// As the explorer narrows, the modified time column goes first, then the
+/// size column, then each level is indented by one column instead of two,
+/// and only then are names truncated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct DetailsLayout {
+    /// Columns per indentation level
+    indent_step: usize,
+    /// Width of the size column (0 once dropped)
+    size_width: usize,
+    /// Width of the modified time column (0 once dropped)
+    time_width: usize,
+}
+
+impl DetailsLayout {
+    /// Fit columns of `size_width` and `time_width` next to rows nested
+    /// `max_depth` levels deep in `content_width`
+    fn fit(content_width: usize, max_depth: usize, size_width: usize, time_width: usize) -> Self {
+        let fits = |indent_step: usize, columns: usize| {
+            max_depth * indent_step + INDICATOR_WIDTH + MIN_NAME_WIDTH + columns <= content_width
+        };
+        let (size_width, time_width) = if fits(2, 2 + size_width + time_width) {
+            (size_width, time_width)
+        } else if fits(2, 1 + size_width) {
+            (size_width, 0)
+        } else {
+            (0, 0)
+        };
+        let mut layout = Self {
+            indent_step: 2,
+            size_width,
+            time_width,
+        };
+        if !fits(2, layout.columns_width()) {
+            layout.indent_step = 1;
+        }
+        layout
+    }
+
+    /// Width of the columns right of the name, with a space before each
+    fn columns_width(&self) -> usize {
+        [self.size_width, self.time_width]
+            .iter()
+            .filter(|width| **width > 0)
+            .map(|width| width + 1)
+            .sum()
+    }
+
+    /// Spans of the details columns, each right-aligned in its column
+    fn column_spans(&self, size: &str, time: &str, style: Style) -> Vec<Span<'static>> {
+        let mut spans = Vec::new();
+        for (text, width) in [(size, self.size_width), (time, self.time_width)] {
+            if width > 0 {
+                let padding = (width + 1).saturating_sub(str_width(text));
+                spans.push(Span::raw(" ".repeat(padding.max(1))));
+                spans.push(Span::styled(text.to_string(), style));
+            }
+        }
+        spans
+    }
+}
+
+/// Texts of the details columns of an entry
+#[derive(Debug, Default)]
+struct DetailCells {
+    size: String,
+    modified: String,
+}
 
 pub struct FileExplorerRenderer;
 
@@ -41,53 +125,118 @@ impl FileExplorerRenderer {
         close_button_hovered: bool,
     ) {
         // Update viewport height for scrolling calculations
-        // Account for borders (top + bottom = 2)
-        let viewport_height = area.height.saturating_sub(2) as usize;
+        // Account for borders (top + bottom = 2) and the header row
+        let header_rows = view.header_rows();
+        let viewport_height = (area.height.saturating_sub(2) as usize).saturating_sub(header_rows);
         view.set_viewport_height(viewport_height);
 
-        let display_nodes = view.get_display_nodes();
+        let display_rows = view.get_display_rows();
         let scroll_offset = view.get_scroll_offset();
         let selected_index = view.get_selected_index();
 
         // Clamp scroll_offset to valid range to prevent panic after tree mutations
         // (e.g., when deleting a folder with many children while scrolled down)
-        // Issue #562: scroll_offset can become larger than display_nodes.len()
-        let scroll_offset = scroll_offset.min(display_nodes.len());
+        // Issue #562: scroll_offset can become larger than display_rows.len()
+        let scroll_offset = scroll_offset.min(display_rows.len());
 
         // Only render the visible subset of items (for manual scroll control)
         // This prevents ratatui's List widget from auto-scrolling
-        let visible_end = (scroll_offset + viewport_height).min(display_nodes.len());
-        let visible_items = &display_nodes[scroll_offset..visible_end];
+        let visible_end = (scroll_offset + viewport_height).min(display_rows.len());
+        let visible_items = &display_rows[scroll_offset..visible_end];
 
         // Available width for content (subtract borders and cursor indicator)
         let content_width = area.width.saturating_sub(3) as usize;
 
-        // Create list items for visible nodes only
-        let items: Vec<ListItem> = visible_items
-            .iter()
-            .enumerate()
-            .map(|(viewport_idx, &(node_id, indent))| {
-                // The actual index in the full list
-                let actual_idx = scroll_offset + viewport_idx;
-                let is_selected = selected_index == Some(actual_idx);
-                Self::render_node(
-                    view,
-                    node_id,
-                    indent,
-                    is_selected,
-                    is_focused,
-                    files_with_unsaved_changes,
-                    theme,
-                    content_width,
-                )
-            })
-            .collect();
+        let show_all_key = keybinding_resolver
+            .get_keybinding_for_action(&Action::FileExplorerShowAll, current_context);
+
+        // Details columns are as wide as their widest cell on screen
+        let (cells, details) = if view.show_details() {
+            let now = SystemTime::now();
+            let cells: Vec<Option<DetailCells>> = visible_items
+                .iter()
+                .map(|(row, _)| row.node_id().map(|id| Self::detail_cells(view, id, now)))
+                .collect();
+            let (_, size_label, time_label) = Self::header_labels(view);
+            let size_width = cells
+                .iter()
+                .flatten()
+                .map(|cells| str_width(&cells.size))
+                .chain([str_width(&size_label)])
+                .max()
+                .unwrap_or(0);
+            let time_width = cells
+                .iter()
+                .flatten()
+                .map(|cells| str_width(&cells.modified))
+                .chain([str_width(&time_label)])
+                .max()
+                .unwrap_or(0);
+            let max_depth = visible_items
+                .iter()
+                .map(|(_, indent)| *indent)
+                .max()
+                .unwrap_or(0);
+            let layout = DetailsLayout::fit(content_width, max_depth, size_width, time_width);
+            (cells, Some(layout))
+        } else {
+            (Vec::new(), None)
+        };
+
+        // Create list items for visible rows only
+        let mut items: Vec<ListItem> = Vec::with_capacity(header_rows + visible_items.len());
+        if header_rows > 0 {
+            items.push(Self::render_header(
+                view,
+                details.as_ref(),
+                content_width,
+                theme,
+            ));
+        }
+        items.extend(
+            visible_items
+                .iter()
+                .enumerate()
+                .map(|(viewport_idx, &(row, indent))| {
+                    // The actual index in the full list
+                    let actual_idx = scroll_offset + viewport_idx;
+                    let is_selected = selected_index == Some(actual_idx);
+                    match row {
+                        TreeRow::Node(node_id) => Self::render_node(
+                            view,
+                            node_id,
+                            indent,
+                            is_selected,
+                            is_focused,
+                            files_with_unsaved_changes,
+                            theme,
+                            content_width,
+                            details
+                                .as_ref()
+                                .zip(cells.get(viewport_idx).and_then(Option::as_ref)),
+                        ),
+                        TreeRow::Loading(_) => Self::render_footer(
+                            t!("explorer.loading_footer").to_string(),
+                            indent,
+                            theme,
+                        ),
+                        TreeRow::MoreEntries { hidden, .. } => {
+                            let text = match &show_all_key {
+                                Some(key) => {
+                                    t!("explorer.more_entries_key", count = hidden, key = key)
+                                }
+                                None => t!("explorer.more_entries", count = hidden),
+                            };
+                            Self::render_footer(text.to_string(), indent, theme)
+                        }
+                    }
+                }),
+        );
 
         // Build the title with keybinding
-        let title = if let Some(keybinding) = keybinding_resolver.get_keybinding_for_action(
-            &crate::input::keybindings::Action::FocusFileExplorer,
-            current_context,
-        ) {
+        let title = if let Some(keybinding) = keybinding_resolver
+            .get_keybinding_for_action(&Action::FocusFileExplorer, current_context)
+        {
             format!(" File Explorer ({}) ", keybinding)
         } else {
             " File Explorer ".to_string()
@@ -95,11 +244,17 @@ impl FileExplorerRenderer {
 
         // Title style: inverted colors (dark on light) when focused using theme colors
         let (title_style, border_style) = if is_focused {
-            (
+            // With the background left to the terminal, let the terminal
+            // invert its own colors instead
+            let inverted = if theme.editor_bg == Color::Reset {
                 Style::default()
-                    .fg(theme.editor_bg)
-                    .bg(theme.editor_fg)
-                    .add_modifier(Modifier::BOLD),
+                    .fg(theme.editor_fg)
+                    .add_modifier(Modifier::REVERSED)
+            } else {
+                Style::default().fg(theme.editor_bg).bg(theme.editor_fg)
+            };
+            (
+                inverted.add_modifier(Modifier::BOLD),
                 Style::default().fg(theme.cursor),
             )
         } else {
@@ -114,6 +269,7 @@ impl FileExplorerRenderer {
             .block(
                 Block::default()
                     .borders(Borders::ALL)
+                    .border_set(glyphs().border_set())
                     .title(title)
                     .title_style(title_style)
                     .border_style(border_style)
@@ -131,7 +287,7 @@ impl FileExplorerRenderer {
         if let Some(selected) = selected_index {
             if selected >= scroll_offset && selected < scroll_offset + viewport_height {
                 // Selected item is in the visible range
-                list_state.select(Some(selected - scroll_offset));
+                list_state.select(Some(selected - scroll_offset + header_rows));
             }
         }
 
@@ -145,7 +301,7 @@ impl FileExplorerRenderer {
             theme.line_number_fg
         };
         let close_button =
-            ratatui::widgets::Paragraph::new("×").style(Style::default().fg(close_fg));
+            ratatui::widgets::Paragraph::new(glyphs().close).style(Style::default().fg(close_fg));
         let close_area = Rect::new(close_button_x, area.y, 1, 1);
         frame.render_widget(close_button, close_area);
 
@@ -157,10 +313,10 @@ impl FileExplorerRenderer {
                 if selected >= scroll_offset && selected < scroll_offset + viewport_height {
                     // Position at the left edge of the selected row (after border)
                     let cursor_x = area.x + 1;
-                    let cursor_y = area.y + 1 + (selected - scroll_offset) as u16;
+                    let cursor_y = area.y + 1 + (header_rows + selected - scroll_offset) as u16;
 
                     // Render a cursor indicator character that the hardware cursor will blink over
-                    let cursor_indicator = ratatui::widgets::Paragraph::new("▌")
+                    let cursor_indicator = ratatui::widgets::Paragraph::new(glyphs().cursor_bar)
                         .style(Style::default().fg(theme.cursor));
                     let cursor_area = ratatui::layout::Rect::new(cursor_x, cursor_y, 1, 1);
                     frame.render_widget(cursor_indicator, cursor_area);
@@ -182,21 +338,32 @@ impl FileExplorerRenderer {
         files_with_unsaved_changes: &HashSet<PathBuf>,
         theme: &Theme,
         content_width: usize,
+        details: Option<(&DetailsLayout, &DetailCells)>,
     ) -> ListItem<'static> {
         let node = view.tree().get_node(node_id).expect("Node should exist");
+        let glyphs = glyphs();
 
         // Build the line with indentation and tree structure
         let mut spans = Vec::new();
 
         // Calculate the left side width for padding calculation
-        let indent_width = indent * 2;
-        let indicator_width = 2; // "▼ " or "● " or "  "
-        let name_width = str_width(&node.entry.name);
+        let indent_width = indent * details.map_or(2, |(layout, _)| layout.indent_step);
+        let indicator_width = INDICATOR_WIDTH; // "▼ " or "● " or "  "
+                                               // File names may hold any byte but `/`, control characters included
+        let mut name =
+            sanitize_line(&node.entry.name, MAX_LABEL_CHARS, glyphs.ellipsis).into_owned();
+        if let Some((layout, _)) = details {
+            let room = content_width
+                .saturating_sub(indent_width + indicator_width + layout.columns_width())
+                .max(1);
+            name = truncate_end(&name, room, glyphs.ellipsis);
+        }
+        let name_width = str_width(&name);
         let left_side_width = indent_width + indicator_width + name_width;
 
         // Indentation
-        if indent > 0 {
-            spans.push(Span::raw("  ".repeat(indent)));
+        if indent_width > 0 {
+            spans.push(Span::raw(" ".repeat(indent_width)));
         }
 
         // Tree expansion indicator (only for directories)
@@ -206,11 +373,11 @@ impl FileExplorerRenderer {
                 Self::folder_has_modified_files(&node.entry.path, files_with_unsaved_changes);
 
             let indicator = if node.is_expanded() {
-                "▼"
+                glyphs.expanded
             } else if node.is_collapsed() {
-                ">"
+                glyphs.collapsed
             } else if node.is_loading() {
-                "⟳"
+                glyphs.loading
             } else {
                 "!"
             };
@@ -222,7 +389,7 @@ impl FileExplorerRenderer {
             // Show modified indicator (small dot) if folder contains modified files
             if has_modified {
                 spans.push(Span::styled(
-                    "●",
+                    glyphs.modified,
                     Style::default().fg(theme.diagnostic_warning_fg),
                 ));
             } else {
@@ -232,7 +399,7 @@ impl FileExplorerRenderer {
             // For files, show unsaved change indicator if applicable
             if files_with_unsaved_changes.contains(&node.entry.path) {
                 spans.push(Span::styled(
-                    "● ",
+                    format!("{} ", glyphs.modified),
                     Style::default().fg(theme.diagnostic_warning_fg),
                 ));
             } else {
@@ -257,27 +424,18 @@ impl FileExplorerRenderer {
             Style::default().fg(theme.editor_fg)
         };
 
-        spans.push(Span::styled(node.entry.name.clone(), name_style));
-
-        // Size info for files, entry count for expanded directories (right-aligned)
-        let size_str = if node.is_file() {
-            node.entry
-                .metadata
-                .as_ref()
-                .and_then(|m| m.size)
-                .map(|size| Self::format_size(size))
-        } else if node.is_expanded() {
-            let count = node.children.len();
-            Some(if count == 1 {
-                "1 item".to_string()
-            } else {
-                format!("{} items", count)
-            })
-        } else {
-            None
-        };
+        spans.push(Span::styled(name, name_style));
 
-        if let Some(size_text) = size_str {
+        if let Some((layout, cells)) = details {
+            // Details columns, right-aligned
+            let padding = content_width.saturating_sub(left_side_width + layout.columns_width());
+            spans.push(Span::raw(" ".repeat(padding)));
+            spans.extend(layout.column_spans(
+                &cells.size,
+                &cells.modified,
+                Style::default().fg(theme.line_number_fg),
+            ));
+        } else if let Some(size_text) = Self::size_badge(view, node_id) {
             let size_display_width = str_width(&size_text);
             // Calculate padding needed for right-alignment
             // We need at least 1 space between name and size
@@ -306,6 +464,129 @@ impl FileExplorerRenderer {
         ListItem::new(Line::from(spans)).style(Style::default().bg(theme.editor_bg))
     }
 
+    /// Size of a file, or number of entries of a directory that has been
+    /// read or counted
+    fn size_badge(view: &FileTreeView, node_id: NodeId) -> Option<String> {
+        let node = view.tree().get_node(node_id)?;
+        if node.is_file() {
+            node.entry
+                .metadata
+                .as_ref()
+                .and_then(|m| m.size)
+                .map(Self::format_size)
+        } else if node.is_expanded() {
+            Some(Self::format_item_count(node.children.len()))
+        } else if node.is_collapsed() {
+            // Entry count badge, once counted in the background
+            view.entry_count(&node.entry.path)
+                .map(Self::format_item_count)
+        } else {
+            None
+        }
+    }
+
+    /// Texts of the details columns of an entry; empty until its metadata
+    /// has been fetched
+    fn detail_cells(view: &FileTreeView, node_id: NodeId, now: SystemTime) -> DetailCells {
+        let modified = view
+            .tree()
+            .get_node(node_id)
+            .and_then(|node| node.entry.metadata.as_ref())
+            .and_then(|metadata| metadata.modified)
+            .map(|modified| format_relative_time(modified, now));
+        DetailCells {
+            size: Self::size_badge(view, node_id).unwrap_or_default(),
+            modified: modified.unwrap_or_default(),
+        }
+    }
+
+    /// Labels of the name, size and modified time columns, the one sorted
+    /// by marked with the direction
+    fn header_labels(view: &FileTreeView) -> (String, String, String) {
+        let sort = view.sort();
+        let arrow = if sort.descending {
+            glyphs().arrow_down
+        } else {
+            glyphs().arrow_up
+        };
+        let label = |mode: SortMode, text: String| {
+            if sort.mode == mode {
+                format!("{} {}", text, arrow)
+            } else {
+                text
+            }
+        };
+        (
+            label(SortMode::Name, t!("explorer.column_name").to_string()),
+            label(SortMode::Size, t!("explorer.column_size").to_string()),
+            label(
+                SortMode::Modified,
+                t!("explorer.column_modified").to_string(),
+            ),
+        )
+    }
+
+    /// Render the header row naming the columns and the sort
+    ///
+    /// Without the details columns, the sort is shown on the right unless
+    /// it is by name.
+    fn render_header(
+        view: &FileTreeView,
+        details: Option<&DetailsLayout>,
+        content_width: usize,
+        theme: &Theme,
+    ) -> ListItem<'static> {
+        let style = Style::default()
+            .fg(theme.line_number_fg)
+            .add_modifier(Modifier::BOLD);
+        let (name, size, time) = Self::header_labels(view);
+        let left = " ".repeat(INDICATOR_WIDTH) + &name;
+        let left_width = str_width(&left);
+        let mut spans = vec![Span::styled(left, style)];
+        match details {
+            Some(layout) => {
+                let padding = content_width.saturating_sub(left_width + layout.columns_width());
+                spans.push(Span::raw(" ".repeat(padding)));
+                spans.extend(layout.column_spans(&size, &time, style));
+            }
+            None => {
+                let sorted = match view.sort().mode {
+                    SortMode::Name => String::new(),
+                    SortMode::Size => size,
+                    SortMode::Modified => time,
+                };
+                let padding = content_width
+                    .saturating_sub(left_width + str_width(&sorted))
+                    .max(1);
+                spans.push(Span::raw(" ".repeat(padding)));
+                spans.push(Span::styled(sorted, style));
+            }
+        }
+        ListItem::new(Line::from(spans)).style(Style::default().bg(theme.editor_bg))
+    }
+
+    /// Render a footer row under a directory ("loading…", "N more not shown")
+    fn render_footer(text: String, indent: usize, theme: &Theme) -> ListItem<'static> {
+        let spans = vec![
+            Span::raw("  ".repeat(indent + 1)),
+            Span::styled(
+                glyphs().text(&text).into_owned(),
+                Style::default()
+                    .fg(theme.line_number_fg)
+                    .add_modifier(Modifier::ITALIC),
+            ),
+        ];
+        ListItem::new(Line::from(spans)).style(Style::default().bg(theme.editor_bg))
+    }
+
+    fn format_item_count(count: usize) -> String {
+        if count == 1 {
+            "1 item".to_string()
+        } else {
+            format!("{} items", count)
+        }
+    }
+
     /// Format file size for display
     /// - Uses 1 decimal place max
     /// - All sizes shown in KB/MB/GB (no bytes) for alignment
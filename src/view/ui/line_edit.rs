@@ -0,0 +1,654 @@
+//! Reusable single-line text editing state
+//!
+//! This module provides `LineEdit`, the editing state behind single-line
+//! inputs in the settings UI (text fields, the map "add entry" row):
+//! - Cursor navigation by grapheme, word, and home/end
+//! - Selection support (Shift variants, select all)
+//! - Cut/copy of the selection and paste with control characters stripped
+//! - Reverting to the value the field had when editing started
+//! - Key handling shared by all of those inputs (`LineEdit::handle_key`)
+//!
+//! The cursor is a byte offset into the value and always sits on a char boundary.
+
+use std::ops::Range;
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+use crate::primitives::display_width::{char_width, str_width};
+use crate::primitives::grapheme;
+use crate::primitives::word_navigation::{find_word_end_bytes, is_word_char};
+
+/// Single-line text editing state
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LineEdit {
+    /// Current text
+    pub value: String,
+    /// Cursor position (byte offset)
+    pub cursor: usize,
+    /// Selection anchor (byte offset) - the selection spans anchor..cursor
+    pub selection_anchor: Option<usize>,
+    /// Value when editing started, restored by `revert`
+    original: Option<String>,
+}
+
+/// The part of a `LineEdit` that fits in a field of a given width
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VisibleLine {
+    /// Visible text as runs of (text, is_selected)
+    pub runs: Vec<(String, bool)>,
+    /// Cursor column relative to the start of the field
+    pub cursor_col: usize,
+}
+
+impl LineEdit {
+    /// Create an empty line edit
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create from initial text, with the cursor at the end
+    pub fn with_value(value: impl Into<String>) -> Self {
+        let value = value.into();
+        Self {
+            cursor: value.len(),
+            value,
+            selection_anchor: None,
+            original: None,
+        }
+    }
+
+    /// Check if the text is empty
+    pub fn is_empty(&self) -> bool {
+        self.value.is_empty()
+    }
+
+    /// Replace the text, moving the cursor to the end
+    pub fn set_value(&mut self, value: impl Into<String>) {
+        self.value = value.into();
+        self.cursor = self.value.len();
+        self.selection_anchor = None;
+    }
+
+    /// Clear the text
+    pub fn clear(&mut self) {
+        self.value.clear();
+        self.cursor = 0;
+        self.selection_anchor = None;
+    }
+
+    /// Take the text, leaving the field empty
+    pub fn take(&mut self) -> String {
+        self.cursor = 0;
+        self.selection_anchor = None;
+        std::mem::take(&mut self.value)
+    }
+
+    // ========================================================================
+    // Revert
+    // ========================================================================
+
+    /// Remember the current value so `revert` can restore it (call on focus)
+    pub fn begin_edit(&mut self) {
+        self.original = Some(self.value.clone());
+    }
+
+    /// Forget the value remembered by `begin_edit`
+    pub fn end_edit(&mut self) {
+        self.original = None;
+    }
+
+    /// Whether the field is between `begin_edit` and `end_edit`
+    pub fn is_editing(&self) -> bool {
+        self.original.is_some()
+    }
+
+    /// Restore the value from when editing started.
+    ///
+    /// Returns true if the value changed.
+    pub fn revert(&mut self) -> bool {
+        match &self.original {
+            Some(original) if *original != self.value => {
+                let original = original.clone();
+                self.set_value(original);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    // ========================================================================
+    // Cursor movement
+    // ========================================================================
+
+    fn move_to(&mut self, pos: usize, selecting: bool) {
+        if selecting {
+            if self.selection_anchor.is_none() {
+                self.selection_anchor = Some(self.cursor);
+            }
+        } else {
+            self.selection_anchor = None;
+        }
+        self.cursor = pos;
+    }
+
+    fn prev_pos(&self) -> usize {
+        grapheme::prev_grapheme_boundary(&self.value, self.cursor)
+    }
+
+    fn next_pos(&self) -> usize {
+        grapheme::next_grapheme_boundary(&self.value, self.cursor)
+    }
+
+    /// Start of the word before the cursor, skipping non-word chars first.
+    ///
+    /// Word chars are ASCII, so the result is always a char boundary.
+    fn word_start_pos(&self) -> usize {
+        let bytes = self.value.as_bytes();
+        let mut pos = self.cursor;
+        while pos > 0 && !is_word_char(bytes[pos - 1]) {
+            pos -= 1;
+        }
+        while pos > 0 && is_word_char(bytes[pos - 1]) {
+            pos -= 1;
+        }
+        pos
+    }
+
+    /// End of the word at or after the cursor
+    fn word_end_pos(&self) -> usize {
+        find_word_end_bytes(self.value.as_bytes(), self.cursor)
+    }
+
+    /// Move cursor left (to previous grapheme cluster boundary)
+    pub fn move_left(&mut self) {
+        // With a selection, Left collapses it to its start
+        match self.selection_range() {
+            Some(range) => self.move_to(range.start, false),
+            None => self.move_to(self.prev_pos(), false),
+        }
+    }
+
+    /// Move cursor right (to next grapheme cluster boundary)
+    pub fn move_right(&mut self) {
+        match self.selection_range() {
+            Some(range) => self.move_to(range.end, false),
+            None => self.move_to(self.next_pos(), false),
+        }
+    }
+
+    /// Move cursor to start
+    pub fn move_home(&mut self) {
+        self.move_to(0, false);
+    }
+
+    /// Move cursor to end
+    pub fn move_end(&mut self) {
+        self.move_to(self.value.len(), false);
+    }
+
+    /// Move to start of previous word
+    pub fn move_word_left(&mut self) {
+        self.move_to(self.word_start_pos(), false);
+    }
+
+    /// Move to end of next word
+    pub fn move_word_right(&mut self) {
+        self.move_to(self.word_end_pos(), false);
+    }
+
+    /// Move cursor left with selection (Shift+Left)
+    pub fn move_left_selecting(&mut self) {
+        self.move_to(self.prev_pos(), true);
+    }
+
+    /// Move cursor right with selection (Shift+Right)
+    pub fn move_right_selecting(&mut self) {
+        self.move_to(self.next_pos(), true);
+    }
+
+    /// Move to start with selection (Shift+Home)
+    pub fn move_home_selecting(&mut self) {
+        self.move_to(0, true);
+    }
+
+    /// Move to end with selection (Shift+End)
+    pub fn move_end_selecting(&mut self) {
+        self.move_to(self.value.len(), true);
+    }
+
+    /// Move word left with selection (Ctrl+Shift+Left)
+    pub fn move_word_left_selecting(&mut self) {
+        self.move_to(self.word_start_pos(), true);
+    }
+
+    /// Move word right with selection (Ctrl+Shift+Right)
+    pub fn move_word_right_selecting(&mut self) {
+        self.move_to(self.word_end_pos(), true);
+    }
+
+    // ========================================================================
+    // Selection
+    // ========================================================================
+
+    /// Check if there's a non-empty selection
+    pub fn has_selection(&self) -> bool {
+        self.selection_range().is_some()
+    }
+
+    /// Selected byte range, if the selection is non-empty
+    pub fn selection_range(&self) -> Option<Range<usize>> {
+        let anchor = self.selection_anchor?;
+        if anchor == self.cursor {
+            return None;
+        }
+        Some(anchor.min(self.cursor)..anchor.max(self.cursor))
+    }
+
+    /// Get the selected text
+    pub fn selected_text(&self) -> Option<&str> {
+        self.selection_range().map(|range| &self.value[range])
+    }
+
+    /// Clear the selection without deleting text
+    pub fn clear_selection(&mut self) {
+        self.selection_anchor = None;
+    }
+
+    /// Select all text (Ctrl+A)
+    pub fn select_all(&mut self) {
+        self.selection_anchor = Some(0);
+        self.cursor = self.value.len();
+    }
+
+    /// Delete the selection and return the deleted text
+    pub fn delete_selection(&mut self) -> Option<String> {
+        let range = self.selection_range()?;
+        let deleted = self.value[range.clone()].to_string();
+        self.value.drain(range.clone());
+        self.cursor = range.start;
+        self.selection_anchor = None;
+        Some(deleted)
+    }
+
+    /// Copy the selection (Ctrl+C)
+    pub fn copy(&self) -> Option<String> {
+        self.selected_text().map(str::to_string)
+    }
+
+    /// Cut the selection (Ctrl+X)
+    pub fn cut(&mut self) -> Option<String> {
+        self.delete_selection()
+    }
+
+    // ========================================================================
+    // Editing operations
+    // ========================================================================
+
+    /// Insert a character at the cursor, replacing the selection.
+    ///
+    /// Control characters are ignored.
+    pub fn insert(&mut self, c: char) {
+        if c.is_control() {
+            return;
+        }
+        self.delete_selection();
+        self.value.insert(self.cursor, c);
+        self.cursor += c.len_utf8();
+    }
+
+    /// Insert a string at the cursor, replacing the selection.
+    ///
+    /// Control characters (including newlines and tabs) are stripped, so
+    /// pasting multi-line text yields a single line.
+    pub fn insert_str(&mut self, s: &str) {
+        let text: String = s.chars().filter(|c| !c.is_control()).collect();
+        self.delete_selection();
+        self.value.insert_str(self.cursor, &text);
+        self.cursor += text.len();
+    }
+
+    /// Delete the character before the cursor, or the selection (Backspace)
+    pub fn backspace(&mut self) {
+        if self.delete_selection().is_some() || self.cursor == 0 {
+            return;
+        }
+        let prev = self.value[..self.cursor]
+            .char_indices()
+            .next_back()
+            .map(|(i, _)| i)
+            .unwrap_or(0);
+        self.value.drain(prev..self.cursor);
+        self.cursor = prev;
+    }
+
+    /// Delete the grapheme cluster at the cursor, or the selection (Delete)
+    pub fn delete(&mut self) {
+        if self.delete_selection().is_some() || self.cursor >= self.value.len() {
+            return;
+        }
+        let next = self.next_pos();
+        self.value.drain(self.cursor..next);
+    }
+
+    /// Delete from start of word to cursor (Ctrl+Backspace)
+    pub fn delete_word_backward(&mut self) {
+        if self.delete_selection().is_some() {
+            return;
+        }
+        let start = self.word_start_pos();
+        self.value.drain(start..self.cursor);
+        self.cursor = start;
+    }
+
+    /// Delete from cursor to end of word (Ctrl+Delete)
+    pub fn delete_word_forward(&mut self) {
+        if self.delete_selection().is_some() {
+            return;
+        }
+        let end = self.word_end_pos();
+        self.value.drain(self.cursor..end);
+    }
+
+    // ========================================================================
+    // Key handling
+    // ========================================================================
+
+    /// Apply an editing key to the field.
+    ///
+    /// Handles typing, Backspace/Delete (Ctrl for words), Left/Right (Ctrl for
+    /// words), Home/End, their Shift-selecting variants, Ctrl+A to select all,
+    /// and Ctrl+Z to revert. Clipboard keys are left to the caller, which owns
+    /// the clipboard.
+    ///
+    /// Returns `Some(true)` if the text changed, `Some(false)` if only the
+    /// cursor or selection moved, and `None` if the key isn't an editing key.
+    pub fn handle_key(&mut self, key: &KeyEvent) -> Option<bool> {
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        let shift = key.modifiers.contains(KeyModifiers::SHIFT);
+        let alt = key.modifiers.contains(KeyModifiers::ALT);
+        let before = self.value.len();
+
+        match key.code {
+            KeyCode::Char('a') | KeyCode::Char('A') if ctrl => {
+                self.select_all();
+                return Some(false);
+            }
+            KeyCode::Char('z') | KeyCode::Char('Z') if ctrl => return Some(self.revert()),
+            KeyCode::Char(_) if ctrl || alt => return None,
+            KeyCode::Char(c) => {
+                self.insert(c);
+                return Some(true);
+            }
+            KeyCode::Backspace if ctrl => self.delete_word_backward(),
+            KeyCode::Backspace => self.backspace(),
+            KeyCode::Delete if ctrl => self.delete_word_forward(),
+            KeyCode::Delete => self.delete(),
+            KeyCode::Left => match (ctrl, shift) {
+                (true, true) => self.move_word_left_selecting(),
+                (true, false) => self.move_word_left(),
+                (false, true) => self.move_left_selecting(),
+                (false, false) => self.move_left(),
+            },
+            KeyCode::Right => match (ctrl, shift) {
+                (true, true) => self.move_word_right_selecting(),
+                (true, false) => self.move_word_right(),
+                (false, true) => self.move_right_selecting(),
+                (false, false) => self.move_right(),
+            },
+            KeyCode::Home if shift => self.move_home_selecting(),
+            KeyCode::Home => self.move_home(),
+            KeyCode::End if shift => self.move_end_selecting(),
+            KeyCode::End => self.move_end(),
+            _ => return None,
+        }
+        // Only deletions remain: they changed the text iff it got shorter
+        Some(self.value.len() != before)
+    }
+
+    // ========================================================================
+    // Rendering support
+    // ========================================================================
+
+    /// The part of the text visible in a field `width` columns wide.
+    ///
+    /// The field scrolls horizontally so the cursor is always visible.
+    pub fn visible(&self, width: usize) -> VisibleLine {
+        let cursor_col = str_width(&self.value[..self.cursor.min(self.value.len())]);
+        let scroll = if width > 0 && cursor_col >= width {
+            cursor_col + 1 - width
+        } else {
+            0
+        };
+        let selection = self.selection_range();
+
+        // A wide char straddling the scroll offset is skipped entirely, so the
+        // field actually starts at the first char at or after the offset
+        let mut start = None;
+        let mut runs: Vec<(String, bool)> = Vec::new();
+        let mut col = 0;
+        for (idx, ch) in self.value.char_indices() {
+            let w = char_width(ch);
+            if col < scroll {
+                col += w;
+                continue;
+            }
+            let field_start = *start.get_or_insert(col);
+            if col + w - field_start > width {
+                break;
+            }
+            col += w;
+            let selected = selection.as_ref().is_some_and(|r| r.contains(&idx));
+            match runs.last_mut() {
+                Some((text, sel)) if *sel == selected => text.push(ch),
+                _ => runs.push((ch.to_string(), selected)),
+            }
+        }
+
+        VisibleLine {
+            runs,
+            cursor_col: cursor_col.saturating_sub(start.unwrap_or(scroll)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_insert_and_backspace() {
+        let mut edit = LineEdit::new();
+        edit.insert('a');
+        edit.insert('©');
+        assert_eq!(edit.value, "a©");
+        assert_eq!(edit.cursor, 3);
+
+        edit.backspace();
+        assert_eq!(edit.value, "a");
+        assert_eq!(edit.cursor, 1);
+    }
+
+    #[test]
+    fn test_insert_str_strips_control_chars() {
+        let mut edit = LineEdit::new();
+        edit.insert_str("foo\nbar\tbaz\r\n");
+        assert_eq!(edit.value, "foobarbaz");
+        assert_eq!(edit.cursor, 9);
+
+        edit.insert('\n');
+        assert_eq!(edit.value, "foobarbaz");
+    }
+
+    #[test]
+    fn test_word_movement() {
+        let mut edit = LineEdit::with_value("hello big world");
+        edit.move_word_left();
+        assert_eq!(edit.cursor, 10);
+        edit.move_word_left();
+        assert_eq!(edit.cursor, 6);
+        edit.move_word_right();
+        assert_eq!(edit.cursor, 9);
+        edit.move_home();
+        edit.move_word_right();
+        assert_eq!(edit.cursor, 5);
+    }
+
+    #[test]
+    fn test_delete_word_backward() {
+        let mut edit = LineEdit::with_value("hello world");
+        edit.delete_word_backward();
+        assert_eq!(edit.value, "hello ");
+        assert_eq!(edit.cursor, 6);
+    }
+
+    #[test]
+    fn test_selection_replace_and_collapse() {
+        let mut edit = LineEdit::with_value("hello world");
+        edit.move_word_left_selecting();
+        assert_eq!(edit.selected_text(), Some("world"));
+
+        edit.insert_str("there");
+        assert_eq!(edit.value, "hello there");
+        assert!(!edit.has_selection());
+
+        edit.move_home_selecting();
+        edit.move_right();
+        assert_eq!(edit.cursor, 11);
+        assert!(!edit.has_selection());
+
+        edit.move_left_selecting();
+        edit.move_left_selecting();
+        edit.move_left();
+        assert_eq!(edit.cursor, 9);
+        assert!(!edit.has_selection());
+    }
+
+    #[test]
+    fn test_cut_and_copy() {
+        let mut edit = LineEdit::with_value("key_name");
+        assert_eq!(edit.copy(), None);
+
+        edit.select_all();
+        assert_eq!(edit.copy(), Some("key_name".to_string()));
+        assert_eq!(edit.value, "key_name");
+
+        assert_eq!(edit.cut(), Some("key_name".to_string()));
+        assert_eq!(edit.value, "");
+        assert_eq!(edit.cursor, 0);
+    }
+
+    #[test]
+    fn test_backspace_and_delete_remove_selection() {
+        let mut edit = LineEdit::with_value("abcdef");
+        edit.move_left_selecting();
+        edit.move_left_selecting();
+        edit.backspace();
+        assert_eq!(edit.value, "abcd");
+
+        edit.move_home();
+        edit.move_right_selecting();
+        edit.delete();
+        assert_eq!(edit.value, "bcd");
+        assert_eq!(edit.cursor, 0);
+    }
+
+    #[test]
+    fn test_revert_to_value_at_begin_edit() {
+        let mut edit = LineEdit::with_value("original");
+        assert!(!edit.revert());
+
+        edit.begin_edit();
+        edit.select_all();
+        edit.insert_str("changed");
+        assert!(edit.revert());
+        assert_eq!(edit.value, "original");
+        assert_eq!(edit.cursor, 8);
+        assert!(!edit.revert());
+
+        edit.end_edit();
+        edit.insert('!');
+        assert!(!edit.revert());
+        assert_eq!(edit.value, "original!");
+    }
+
+    #[test]
+    fn test_handle_key() {
+        let key = |code, modifiers| KeyEvent::new(code, modifiers);
+        let mut edit = LineEdit::new();
+        edit.begin_edit();
+
+        assert_eq!(
+            edit.handle_key(&key(KeyCode::Char('X'), KeyModifiers::SHIFT)),
+            Some(true)
+        );
+        edit.insert_str("yz abc");
+        assert_eq!(
+            edit.handle_key(&key(
+                KeyCode::Left,
+                KeyModifiers::CONTROL | KeyModifiers::SHIFT
+            )),
+            Some(false)
+        );
+        assert_eq!(edit.selected_text(), Some("abc"));
+        assert_eq!(
+            edit.handle_key(&key(KeyCode::Backspace, KeyModifiers::NONE)),
+            Some(true)
+        );
+        assert_eq!(edit.value, "Xyz ");
+        assert_eq!(
+            edit.handle_key(&key(KeyCode::Backspace, KeyModifiers::CONTROL)),
+            Some(true)
+        );
+        assert_eq!(edit.value, "");
+        assert_eq!(
+            edit.handle_key(&key(KeyCode::Delete, KeyModifiers::NONE)),
+            Some(false)
+        );
+
+        // Clipboard and navigation keys are left to the caller
+        assert_eq!(
+            edit.handle_key(&key(KeyCode::Char('v'), KeyModifiers::CONTROL)),
+            None
+        );
+        assert_eq!(
+            edit.handle_key(&key(KeyCode::Enter, KeyModifiers::NONE)),
+            None
+        );
+        assert_eq!(edit.handle_key(&key(KeyCode::Up, KeyModifiers::NONE)), None);
+
+        edit.insert_str("typed");
+        assert_eq!(
+            edit.handle_key(&key(KeyCode::Char('z'), KeyModifiers::CONTROL)),
+            Some(true)
+        );
+        assert_eq!(edit.value, "");
+    }
+
+    #[test]
+    fn test_visible_scrolls_to_cursor() {
+        let edit = LineEdit::with_value("abcdefghij");
+        let visible = edit.visible(5);
+        assert_eq!(visible.runs, vec![("ghij".to_string(), false)]);
+        assert_eq!(visible.cursor_col, 4);
+
+        let mut edit = edit;
+        edit.move_home();
+        edit.move_right_selecting();
+        edit.move_right_selecting();
+        let visible = edit.visible(5);
+        assert_eq!(
+            visible.runs,
+            vec![("ab".to_string(), true), ("cde".to_string(), false)]
+        );
+        assert_eq!(visible.cursor_col, 2);
+    }
+
+    #[test]
+    fn test_visible_wide_chars() {
+        let edit = LineEdit::with_value("日本語");
+        let visible = edit.visible(4);
+        // Cursor at column 6 needs a free column, so only the last char fits
+        assert_eq!(visible.runs, vec![("語".to_string(), false)]);
+        assert_eq!(visible.cursor_col, 2);
+    }
+}
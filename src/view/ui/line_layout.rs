@@ -0,0 +1,260 @@
+//! Per-line layout cache for the source view
+//!
+//! Laying out a viewport means tokenizing, wrapping and converting every
+//! visible source line into `ViewLine`s. Between two renders most of those
+//! lines are unchanged: typing a character touches one line, moving the
+//! cursor touches none. The cache keeps each line's layout keyed by a hash of
+//! its text so only lines whose content changed are laid out again.
+//!
+//! Cached lines store byte offsets relative to the start of their source
+//! line, so a line that moved (because text was inserted above it) is reused
+//! by rebasing its offsets. Decorations (overlays, selections, virtual text)
+//! are applied after layout and never enter the cache, which is why a line's
+//! text is enough to identify its layout.
+//!
+//! Anything that changes how every line is laid out — vertical scrolling,
+//! resizing, toggling wrap, switching buffer or theme — clears the cache.
+//! Horizontal scrolling only shifts what is drawn and keeps it. A split keeps
+//! the caches of buffers it showed recently (see
+//! [`crate::view::split::RetainedViews`]), so switching back to one reuses
+//! its layouts.
+
+use crate::model::buffer::LineEnding;
+use crate::model::event::BufferId;
+use crate::services::plugins::api::ViewTokenStyle;
+use crate::view::ui::view_pipeline::{LineStart, ViewLine};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+
+/// Everything outside a line's own text that affects its layout
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LayoutParams {
+    pub buffer_id: BufferId,
+    pub theme: String,
+    pub top_byte: usize,
+    pub viewport_width: u16,
+    pub viewport_height: u16,
+    pub line_wrap: bool,
+    pub wrap_width: usize,
+    pub gutter_width: usize,
+    pub tab_size: usize,
+    pub line_ending: LineEnding,
+}
+
+#[derive(Debug, Clone)]
+struct CachedLine {
+    /// Display lines for the source line, offsets relative to its start
+    lines: Vec<ViewLine>,
+    /// Rows this line counts against the token budget (1 + forced breaks)
+    rows: usize,
+    /// Pass in which this entry was last used
+    used: u64,
+}
+
+/// Layouts of the source lines in one split's viewport
+#[derive(Debug, Clone, Default)]
+pub struct LineLayoutCache {
+    params: Option<LayoutParams>,
+    lines: HashMap<u64, CachedLine>,
+    pass: u64,
+    /// Source lines laid out from scratch since the cache was created
+    lines_laid_out: usize,
+}
+
+impl LineLayoutCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start a layout pass, clearing the cache if `params` differ from the
+    /// previous pass
+    pub fn begin_pass(&mut self, params: LayoutParams) {
+        if self.params.as_ref() != Some(&params) {
+            self.lines.clear();
+            self.params = Some(params);
+        }
+        self.pass += 1;
+    }
+
+    /// Finish a layout pass, forgetting lines that are no longer on screen
+    pub fn end_pass(&mut self) {
+        let pass = self.pass;
+        self.lines.retain(|_, line| line.used == pass);
+    }
+
+    /// Hash identifying a line's text
+    pub fn key(text: &str) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        text.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    /// Cached rows and layout for the line with `key`, rebased to
+    /// `line_start`. `first` is whether the line opens the viewport.
+    pub fn get(
+        &mut self,
+        key: u64,
+        line_start: usize,
+        first: bool,
+    ) -> Option<(usize, Vec<ViewLine>)> {
+        let cached = self.lines.get_mut(&key)?;
+        cached.used = self.pass;
+        let lines = cached
+            .lines
+            .iter()
+            .enumerate()
+            .map(|(i, line)| {
+                let mut line = line.clone();
+                for source in line.char_source_bytes.iter_mut().flatten() {
+                    *source += line_start;
+                }
+                if i == 0 && !first {
+                    line.line_start = LineStart::AfterSourceNewline;
+                }
+                line
+            })
+            .collect();
+        Some((cached.rows, lines))
+    }
+
+    /// Remember the layout of a line starting at `line_start`
+    pub fn insert(&mut self, key: u64, line_start: usize, rows: usize, lines: &[ViewLine]) {
+        self.lines_laid_out += 1;
+        let lines = lines
+            .iter()
+            .enumerate()
+            .map(|(i, line)| {
+                let mut line = line.clone();
+                for source in line.char_source_bytes.iter_mut().flatten() {
+                    *source -= line_start;
+                }
+                if i == 0 {
+                    line.line_start = LineStart::Beginning;
+                }
+                line
+            })
+            .collect();
+        self.lines.insert(
+            key,
+            CachedLine {
+                lines,
+                rows,
+                used: self.pass,
+            },
+        );
+    }
+
+    /// Count a line that was laid out but could not be cached
+    pub fn record_uncached(&mut self) {
+        self.lines_laid_out += 1;
+    }
+
+    /// Total number of source lines laid out from scratch
+    pub fn lines_laid_out(&self) -> usize {
+        self.lines_laid_out
+    }
+
+    /// Buffer of the last layout pass
+    pub fn buffer_id(&self) -> Option<BufferId> {
+        self.params.as_ref().map(|params| params.buffer_id)
+    }
+
+    /// Rough number of bytes the cached layouts take
+    pub fn approx_bytes(&self) -> usize {
+        self.lines
+            .values()
+            .flat_map(|cached| &cached.lines)
+            .map(|line| {
+                let chars = line.char_source_bytes.len();
+                std::mem::size_of::<ViewLine>()
+                    + line.text.len()
+                    + chars * std::mem::size_of::<Option<usize>>()
+                    + chars * std::mem::size_of::<Option<ViewTokenStyle>>()
+                    + (line.char_visual_cols.len() + line.visual_to_char.len())
+                        * std::mem::size_of::<usize>()
+                    + line.tab_starts.len() * std::mem::size_of::<usize>()
+            })
+            .sum()
+    }
+
+    /// Number of distinct lines currently cached
+    pub fn len(&self) -> usize {
+        self.lines.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lines.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    fn params(top_byte: usize) -> LayoutParams {
+        LayoutParams {
+            buffer_id: BufferId(0),
+            theme: "dark".to_string(),
+            top_byte,
+            viewport_width: 80,
+            viewport_height: 24,
+            line_wrap: false,
+            wrap_width: 80,
+            gutter_width: 4,
+            tab_size: 4,
+            line_ending: LineEnding::LF,
+        }
+    }
+
+    fn line(text: &str, start: usize) -> ViewLine {
+        let len = text.chars().count();
+        ViewLine {
+            text: text.to_string(),
+            char_source_bytes: (start..start + len).map(Some).collect(),
+            char_styles: vec![None; len],
+            char_visual_cols: (0..len).collect(),
+            visual_to_char: (0..len).collect(),
+            tab_starts: HashSet::new(),
+            line_start: LineStart::AfterSourceNewline,
+            ends_with_newline: true,
+        }
+    }
+
+    #[test]
+    fn test_reused_line_is_rebased() {
+        let mut cache = LineLayoutCache::new();
+        cache.begin_pass(params(0));
+        let key = LineLayoutCache::key("abc\n");
+        cache.insert(key, 10, 1, &[line("abc\n", 10)]);
+        cache.end_pass();
+
+        cache.begin_pass(params(0));
+        let (rows, lines) = cache.get(key, 14, false).unwrap();
+        assert_eq!(rows, 1);
+        assert_eq!(lines[0].char_source_bytes[0], Some(14));
+        assert_eq!(lines[0].line_start, LineStart::AfterSourceNewline);
+        let (_, lines) = cache.get(key, 0, true).unwrap();
+        assert_eq!(lines[0].line_start, LineStart::Beginning);
+        assert_eq!(cache.lines_laid_out(), 1);
+    }
+
+    #[test]
+    fn test_param_change_clears_and_unused_lines_expire() {
+        let mut cache = LineLayoutCache::new();
+        cache.begin_pass(params(0));
+        cache.insert(LineLayoutCache::key("a\n"), 0, 1, &[line("a\n", 0)]);
+        cache.insert(LineLayoutCache::key("b\n"), 2, 1, &[line("b\n", 2)]);
+        cache.end_pass();
+        assert_eq!(cache.len(), 2);
+
+        cache.begin_pass(params(0));
+        assert!(cache.get(LineLayoutCache::key("a\n"), 0, true).is_some());
+        cache.end_pass();
+        assert_eq!(cache.len(), 1);
+
+        cache.begin_pass(params(2));
+        assert!(cache.get(LineLayoutCache::key("a\n"), 0, true).is_none());
+    }
+}
@@ -2,6 +2,7 @@
 
 use crate::config::{Menu, MenuConfig, MenuItem};
 use crate::primitives::display_width::str_width;
+use crate::view::glyphs::glyphs;
 use crate::view::theme::Theme;
 use ratatui::layout::Rect;
 use ratatui::style::{Modifier, Style};
@@ -708,15 +709,15 @@ impl MenuRenderer {
 
                     let checkbox_icon = if checkbox.is_some() {
                         if is_checkbox_checked(checkbox, context) {
-                            "☑ "
+                            format!("{} ", glyphs().checkbox_on)
                         } else {
-                            "☐ "
+                            format!("{} ", glyphs().checkbox_off)
                         }
                     } else {
-                        ""
+                        String::new()
                     };
 
-                    let checkbox_width = if checkbox.is_some() { 2 } else { 0 };
+                    let checkbox_width = str_width(&checkbox_icon);
                     let label_display_width = str_width(label);
                     let keybinding_display_width = str_width(&keybinding);
 
@@ -740,7 +741,7 @@ impl MenuRenderer {
                     Line::from(vec![Span::styled(text, style)])
                 }
                 MenuItem::Separator { .. } => {
-                    let separator = "─".repeat(content_width);
+                    let separator = glyphs().border_horizontal.repeat(content_width);
                     Line::from(vec![Span::styled(
                         format!(" {separator}"),
                         Style::default()
@@ -792,6 +793,7 @@ impl MenuRenderer {
 
         let block = Block::default()
             .borders(Borders::ALL)
+            .border_set(glyphs().border_set())
             .border_style(Style::default().fg(theme.menu_border_fg))
             .style(Style::default().bg(theme.menu_dropdown_bg));
 
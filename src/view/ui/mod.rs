@@ -11,9 +11,15 @@
 //! - `scrollbar` - Reusable scrollbar widget
 //! - `scroll_panel` - Reusable scrollable panel for variable-height items
 //! - `file_browser` - File open dialog popup
+//! - `text_edit` / `line_edit` - Reusable multiline and single-line editing state
+//! - `line_layout` - Per-line layout cache for the source view
+//! - `winbar` - Line at the top of a split naming its buffer
+//! - `too_small` - Placeholder for terminals too small for the editor
 
 pub mod file_browser;
 pub mod file_explorer;
+pub mod line_edit;
+pub mod line_layout;
 pub mod menu;
 pub mod menu_input;
 pub mod scroll_panel;
@@ -23,11 +29,15 @@ pub mod status_bar;
 pub mod suggestions;
 pub mod tabs;
 pub mod text_edit;
+pub mod too_small;
 pub mod view_pipeline;
+pub mod whitespace;
+pub mod winbar;
 
 // Re-export main types for convenience
 pub use file_browser::{FileBrowserLayout, FileBrowserRenderer};
 pub use file_explorer::FileExplorerRenderer;
+pub use line_edit::{LineEdit, VisibleLine};
 pub use menu::{context_keys, MenuContext, MenuRenderer, MenuState};
 pub use menu_input::MenuInputHandler;
 pub use scroll_panel::{
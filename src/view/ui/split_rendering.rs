@@ -4,19 +4,27 @@ use std::collections::BTreeMap;
 
 use crate::app::types::ViewLineMapping;
 use crate::app::BufferMetadata;
+use crate::config::{DataPathStyle, WhitespaceRendering, WinbarVisibility};
 use crate::model::buffer::Buffer;
 use crate::model::cursor::SelectionMode;
 use crate::model::event::{BufferId, EventLog, SplitDirection};
 use crate::primitives::ansi::AnsiParser;
 use crate::primitives::ansi_background::AnsiBackground;
-use crate::primitives::display_width::char_width;
+use crate::primitives::data_path::format_path;
+use crate::primitives::display_width::{char_width, str_width};
 use crate::services::plugins::api::ViewTransformPayload;
 use crate::state::{EditorState, ViewMode};
-use crate::view::split::SplitManager;
+use crate::view::glyphs::glyphs;
+use crate::view::overlay::OverlayNamespace;
+use crate::view::split::{is_search_namespace, search_namespace, SplitManager};
+use crate::view::theme::ContrastCache;
+use crate::view::ui::line_layout::{LayoutParams, LineLayoutCache};
 use crate::view::ui::tabs::TabsRenderer;
 use crate::view::ui::view_pipeline::{
     should_show_line_number, LineStart, ViewLine, ViewLineIterator,
 };
+use crate::view::ui::whitespace::{char_marks, symbol_for, CharMark, MarkKind};
+use crate::view::ui::winbar::Winbar;
 use crate::view::virtual_text::VirtualTextPosition;
 use ratatui::layout::Rect;
 use ratatui::style::{Color, Modifier, Style};
@@ -25,6 +33,7 @@ use ratatui::widgets::{Block, Borders, Clear, Paragraph};
 use ratatui::Frame;
 use std::collections::{HashMap, HashSet};
 use std::ops::Range;
+use std::time::Instant;
 
 /// Maximum line width before forced wrapping is applied, even when line wrapping is disabled.
 /// This prevents memory exhaustion when opening files with extremely long lines (e.g., 10MB
@@ -204,11 +213,12 @@ impl DebugSpanTracker {
             // Check if we're entering new overlay spans
             for (overlay, range) in viewport_overlays.iter() {
                 if range.start == bp {
-                    let overlay_type = match &overlay.face {
+                    let overlay_type = match &overlay.face() {
                         crate::view::overlay::OverlayFace::Underline { .. } => "ul",
                         crate::view::overlay::OverlayFace::Background { .. } => "bg",
                         crate::view::overlay::OverlayFace::Foreground { .. } => "fg",
-                        crate::view::overlay::OverlayFace::Style { .. } => "st",
+                        crate::view::overlay::OverlayFace::Style { .. }
+                        | crate::view::overlay::OverlayFace::Themed { .. } => "st",
                     };
                     tags.push(format!("<{}:{}-{}>", overlay_type, range.start, range.end));
                     self.active_overlays.push(range.clone());
@@ -282,6 +292,8 @@ struct DecorationContext {
     diagnostic_lines: HashSet<usize>,
     /// Line indicators indexed by line number (highest priority indicator per line)
     line_indicators: BTreeMap<usize, crate::view::margin::LineIndicator>,
+    /// Indicators of plugin gutter columns, by column name and line number
+    plugin_column_indicators: HashMap<String, BTreeMap<usize, crate::view::margin::LineIndicator>>,
 }
 
 struct LineRenderOutput {
@@ -330,6 +342,13 @@ struct LineRenderInput<'a> {
     left_column: usize,
     /// Whether to show relative line numbers (distance from cursor)
     relative_line_numbers: bool,
+    /// Whitespace drawn with visible symbols
+    render_whitespace: WhitespaceRendering,
+    /// Source column from which text is tinted as too long
+    /// (`ui.highlight_overlength`)
+    overlength_column: Option<usize>,
+    /// Text colors adjusted for `ui.minimum_contrast`
+    contrast: Option<&'a mut ContrastCache>,
 }
 
 /// Context for computing the style of a single character
@@ -353,7 +372,7 @@ struct CharStyleOutput {
     is_secondary_cursor: bool,
 }
 
-/// Context for rendering the left margin (line numbers, indicators, separator)
+/// Context for rendering the left margin (gutter columns and separator)
 struct LeftMarginContext<'a> {
     state: &'a EditorState,
     theme: &'a crate::view::theme::Theme,
@@ -363,71 +382,98 @@ struct LeftMarginContext<'a> {
     diagnostic_lines: &'a HashSet<usize>,
     /// Pre-computed line indicators (line_num -> indicator)
     line_indicators: &'a BTreeMap<usize, crate::view::margin::LineIndicator>,
+    /// Pre-computed indicators of plugin columns (column -> line_num -> indicator)
+    plugin_column_indicators:
+        &'a HashMap<String, BTreeMap<usize, crate::view::margin::LineIndicator>>,
     /// Line number where the primary cursor is located (for relative line numbers)
     cursor_line: usize,
     /// Whether to show relative line numbers
     relative_line_numbers: bool,
 }
 
-/// Render the left margin (indicators + line numbers + separator) to line_spans
+/// Render the left margin (gutter columns + separator) to line_spans
 fn render_left_margin(
     ctx: &LeftMarginContext,
     line_spans: &mut Vec<Span<'static>>,
     line_view_map: &mut Vec<Option<usize>>,
 ) {
-    if !ctx.state.margins.left_config.enabled {
+    use crate::view::margin::GutterColumnKind;
+
+    let config = &ctx.state.margins.left_config;
+    if !config.enabled {
         return;
     }
 
-    // For continuation lines, don't show any indicators
-    if ctx.is_continuation {
+    for column in &config.columns {
+        let width = config.column_width(column);
+        let (text, style) = match &column.kind {
+            GutterColumnKind::Indicators => render_indicator_cell(ctx),
+            GutterColumnKind::LineNumbers => render_line_number_cell(ctx),
+            GutterColumnKind::Plugin(name) => {
+                // For continuation lines, don't show any indicators
+                let indicator = ctx
+                    .plugin_column_indicators
+                    .get(name)
+                    .and_then(|by_line| by_line.get(&ctx.current_source_line_num))
+                    .filter(|_| !ctx.is_continuation);
+                match indicator {
+                    Some(indicator) => (
+                        indicator.symbol.clone(),
+                        Style::default().fg(indicator.color),
+                    ),
+                    None => (String::new(), Style::default()),
+                }
+            }
+        };
         push_span_with_map(
             line_spans,
             line_view_map,
-            " ".to_string(),
-            Style::default(),
+            column.alignment.pad(&text, width),
+            style,
             None,
         );
-    } else if ctx.diagnostic_lines.contains(&ctx.current_source_line_num) {
-        // Diagnostic indicators have highest priority
+    }
+
+    // Render separator
+    if config.show_separator {
+        let separator_style = Style::default().fg(ctx.theme.line_number_fg);
         push_span_with_map(
             line_spans,
             line_view_map,
-            "●".to_string(),
-            Style::default().fg(ratatui::style::Color::Red),
+            config.rendered_separator(),
+            separator_style,
             None,
         );
+    }
+}
+
+/// Content of the indicator column: diagnostics, then line indicators
+fn render_indicator_cell(ctx: &LeftMarginContext) -> (String, Style) {
+    // For continuation lines, don't show any indicators
+    if ctx.is_continuation {
+        (String::new(), Style::default())
+    } else if ctx.diagnostic_lines.contains(&ctx.current_source_line_num) {
+        // Diagnostic indicators have highest priority
+        (
+            glyphs().gutter_dot.to_string(),
+            Style::default().fg(ratatui::style::Color::Red),
+        )
     } else if let Some(indicator) = ctx.line_indicators.get(&ctx.current_source_line_num) {
         // Show line indicator (git gutter, breakpoints, etc.)
-        push_span_with_map(
-            line_spans,
-            line_view_map,
+        (
             indicator.symbol.clone(),
             Style::default().fg(indicator.color),
-            None,
-        );
+        )
     } else {
-        // Show space (no indicator)
-        push_span_with_map(
-            line_spans,
-            line_view_map,
-            " ".to_string(),
-            Style::default(),
-            None,
-        );
+        (String::new(), Style::default())
     }
+}
 
-    // Render line number (right-aligned) or blank for continuations
+/// Content of the line number column, blank for continuations
+fn render_line_number_cell(ctx: &LeftMarginContext) -> (String, Style) {
     if ctx.is_continuation {
         // For wrapped continuation lines, render blank space
-        let blank = " ".repeat(ctx.state.margins.left_config.width);
-        push_span_with_map(
-            line_spans,
-            line_view_map,
-            blank,
-            Style::default().fg(ctx.theme.line_number_fg),
-            None,
-        );
+        (String::new(), Style::default().fg(ctx.theme.line_number_fg))
     } else if ctx.relative_line_numbers {
         // Relative line numbers: show distance from cursor, or absolute for cursor line
         let display_num = if ctx.current_source_line_num == ctx.cursor_line {
@@ -437,18 +483,13 @@ fn render_left_margin(
             // Show relative distance for other lines
             ctx.current_source_line_num.abs_diff(ctx.cursor_line)
         };
-        let rendered_text = format!(
-            "{:>width$}",
-            display_num,
-            width = ctx.state.margins.left_config.width
-        );
         // Use brighter color for the cursor line
         let margin_style = if ctx.current_source_line_num == ctx.cursor_line {
             Style::default().fg(ctx.theme.editor_fg)
         } else {
             Style::default().fg(ctx.theme.line_number_fg)
         };
-        push_span_with_map(line_spans, line_view_map, rendered_text, margin_style, None);
+        (display_num.to_string(), margin_style)
     } else {
         let margin_content = ctx.state.margins.render_line(
             ctx.current_source_line_num,
@@ -460,20 +501,7 @@ fn render_left_margin(
         // Use custom style if provided, otherwise use default theme color
         let margin_style =
             style_opt.unwrap_or_else(|| Style::default().fg(ctx.theme.line_number_fg));
-
-        push_span_with_map(line_spans, line_view_map, rendered_text, margin_style, None);
-    }
-
-    // Render separator
-    if ctx.state.margins.left_config.show_separator {
-        let separator_style = Style::default().fg(ctx.theme.line_number_fg);
-        push_span_with_map(
-            line_spans,
-            line_view_map,
-            ctx.state.margins.left_config.separator.clone(),
-            separator_style,
-            None,
-        );
+        (rendered_text.trim_start().to_string(), margin_style)
     }
 }
 
@@ -565,7 +593,7 @@ fn compute_char_style(ctx: &CharStyleContext) -> CharStyleOutput {
 
     // Apply overlay styles
     for overlay in &overlays {
-        match &overlay.face {
+        match &overlay.face() {
             OverlayFace::Underline {
                 color,
                 style: _underline_style,
@@ -583,6 +611,11 @@ fn compute_char_style(ctx: &CharStyleContext) -> CharStyleOutput {
             } => {
                 style = style.patch(*overlay_style);
             }
+            OverlayFace::Themed {
+                style: overlay_style,
+            } => {
+                style = style.patch(overlay_style.resolve(ctx.theme));
+            }
         }
     }
 
@@ -611,6 +644,35 @@ fn compute_char_style(ctx: &CharStyleContext) -> CharStyleOutput {
     }
 }
 
+/// Color visible whitespace and control character placeholders
+///
+/// `drawn` is whether the character is drawn as a symbol. Trailing
+/// whitespace gets its background unless whitespace rendering is off or the
+/// character is selected.
+fn whitespace_style(
+    style: Style,
+    mark: CharMark,
+    drawn: bool,
+    is_selected: bool,
+    render_whitespace: WhitespaceRendering,
+    theme: &crate::view::theme::Theme,
+) -> Style {
+    let whitespace = &theme.whitespace;
+    let mut style = style;
+    if drawn {
+        style = style.fg(match mark.kind {
+            MarkKind::Space => whitespace.space.fg,
+            MarkKind::TabStart | MarkKind::TabFill => whitespace.tab.fg,
+            MarkKind::UnusualSpace => whitespace.unusual_space.fg,
+            MarkKind::Control => whitespace.control_fg,
+        });
+    }
+    if mark.trailing && !is_selected && render_whitespace != WhitespaceRendering::None {
+        style = style.bg(whitespace.trailing_bg);
+    }
+    style
+}
+
 /// Renders split panes and their content
 pub struct SplitRenderer;
 
@@ -630,6 +692,11 @@ impl SplitRenderer {
     /// * `line_wrap` - Whether line wrapping is enabled
     /// * `estimated_line_length` - Estimated average line length for large file line estimation
     /// * `hide_cursor` - Whether to hide the hardware cursor (e.g., when menu is open)
+    /// * `contrast` - Buffer text colors adjusted for `ui.minimum_contrast`
+    /// * `data_path_style` - Style of the key path line above JSON, YAML and TOML
+    ///   buffers, None to not show it
+    /// * `winbar_visibility` - When splits get a winbar
+    /// * `now` - Current time on the editor's clock, for debounced highlights
     ///
     /// # Returns
     /// * Vec of (split_id, buffer_id, content_rect, scrollbar_rect, thumb_start, thumb_end) for mouse handling
@@ -656,7 +723,15 @@ impl SplitRenderer {
         hovered_close_split: Option<crate::model::event::SplitId>,
         hovered_maximize_split: Option<crate::model::event::SplitId>,
         is_maximized: bool,
+        hide_tabs: bool,
         relative_line_numbers: bool,
+        render_whitespace: WhitespaceRendering,
+        rulers: &[u16],
+        highlight_overlength: bool,
+        contrast: &mut ContrastCache,
+        data_path_style: Option<DataPathStyle>,
+        winbar_visibility: WinbarVisibility,
+        now: Instant,
     ) -> (
         Vec<(
             crate::model::event::SplitId,
@@ -670,6 +745,7 @@ impl SplitRenderer {
         Vec<(crate::model::event::SplitId, u16, u16, u16)>, // close split button areas
         Vec<(crate::model::event::SplitId, u16, u16, u16)>, // maximize split button areas
         HashMap<crate::model::event::SplitId, Vec<ViewLineMapping>>, // view line mappings for mouse clicks
+        Vec<(crate::model::event::SplitId, BufferId, Rect)>,         // winbar areas
     ) {
         let _span = tracing::trace_span!("render_content").entered();
 
@@ -677,6 +753,7 @@ impl SplitRenderer {
         let visible_buffers = split_manager.get_visible_buffers(area);
         let active_split_id = split_manager.active_split();
         let has_multiple_splits = visible_buffers.len() > 1;
+        let show_winbar = winbar_visibility.shows(visible_buffers.len());
 
         // Collect areas for mouse handling
         let mut split_areas = Vec::new();
@@ -685,12 +762,13 @@ impl SplitRenderer {
         let mut maximize_split_areas = Vec::new();
         let mut view_line_mappings: HashMap<crate::model::event::SplitId, Vec<ViewLineMapping>> =
             HashMap::new();
+        let mut winbar_areas = Vec::new();
 
         // Render each split
         for (split_id, buffer_id, split_area) in visible_buffers {
             let is_active = split_id == active_split_id;
 
-            let layout = Self::split_layout(split_area);
+            let mut layout = Self::split_layout(split_area, hide_tabs);
             let (split_buffers, tab_scroll_offset) =
                 Self::split_buffers_for_tabs(split_view_states.as_deref(), split_id, buffer_id);
 
@@ -704,18 +782,22 @@ impl SplitRenderer {
             });
 
             // Render tabs for this split and collect hit areas
-            let tab_hit_areas = TabsRenderer::render_for_split(
-                frame,
-                layout.tabs_rect,
-                &split_buffers,
-                buffers,
-                buffer_metadata,
-                buffer_id, // The currently displayed buffer in this split
-                theme,
-                is_active,
-                tab_scroll_offset,
-                tab_hover_for_split,
-            );
+            let tab_hit_areas = if hide_tabs {
+                Vec::new()
+            } else {
+                TabsRenderer::render_for_split(
+                    frame,
+                    layout.tabs_rect,
+                    &split_buffers,
+                    buffers,
+                    buffer_metadata,
+                    buffer_id, // The currently displayed buffer in this split
+                    theme,
+                    is_active,
+                    tab_scroll_offset,
+                    tab_hover_for_split,
+                )
+            };
 
             // Add tab row to hit areas (all tabs share the same row)
             let tab_row = layout.tabs_rect.y;
@@ -726,14 +808,13 @@ impl SplitRenderer {
             // Render split control buttons at the right side of tabs row
             // Show maximize/unmaximize button when: multiple splits exist OR we're currently maximized
             // Show close button when: multiple splits exist AND we're not maximized
-            let show_maximize_btn = has_multiple_splits || is_maximized;
-            let show_close_btn = has_multiple_splits && !is_maximized;
+            let show_maximize_btn = !hide_tabs && (has_multiple_splits || is_maximized);
+            let show_close_btn = !hide_tabs && has_multiple_splits && !is_maximized;
 
+            // Calculate button positions from right edge
+            // Layout: [lock] [space] [maximize] [space] [close] |
+            let mut btn_x = layout.tabs_rect.x + layout.tabs_rect.width.saturating_sub(2);
             if show_maximize_btn || show_close_btn {
-                // Calculate button positions from right edge
-                // Layout: [maximize] [space] [close] |
-                let mut btn_x = layout.tabs_rect.x + layout.tabs_rect.width.saturating_sub(2);
-
                 // Render close button first (rightmost) if visible
                 if show_close_btn {
                     let is_hovered = hovered_close_split == Some(split_id);
@@ -742,7 +823,7 @@ impl SplitRenderer {
                     } else {
                         theme.line_number_fg
                     };
-                    let close_button = Paragraph::new("×")
+                    let close_button = Paragraph::new(glyphs().close)
                         .style(Style::default().fg(close_fg).bg(theme.tab_separator_bg));
                     let close_area = Rect::new(btn_x, tab_row, 1, 1);
                     frame.render_widget(close_button, close_area);
@@ -758,16 +839,39 @@ impl SplitRenderer {
                     } else {
                         theme.line_number_fg
                     };
-                    // Use □ for maximize, ⧉ for unmaximize (restore)
-                    let icon = if is_maximized { "⧉" } else { "□" };
+                    let icon = if is_maximized {
+                        glyphs().restore
+                    } else {
+                        glyphs().maximize
+                    };
                     let max_button = Paragraph::new(icon)
                         .style(Style::default().fg(max_fg).bg(theme.tab_separator_bg));
                     let max_area = Rect::new(btn_x, tab_row, 1, 1);
                     frame.render_widget(max_button, max_area);
                     maximize_split_areas.push((split_id, tab_row, btn_x, btn_x + 1));
+                    btn_x = btn_x.saturating_sub(2);
                 }
             }
 
+            // Splits with locked input show a lock left of the buttons
+            let input_locked = split_view_states
+                .as_deref()
+                .and_then(|vs| vs.get(&split_id))
+                .is_some_and(|vs| vs.input_locked);
+            if input_locked && !hide_tabs {
+                let lock = glyphs().locked;
+                let lock_width = str_width(lock) as u16;
+                let lock_x = (btn_x + 1)
+                    .saturating_sub(lock_width)
+                    .max(layout.tabs_rect.x);
+                let lock_widget = Paragraph::new(lock).style(
+                    Style::default()
+                        .fg(theme.line_number_fg)
+                        .bg(theme.tab_separator_bg),
+                );
+                frame.render_widget(lock_widget, Rect::new(lock_x, tab_row, lock_width, 1));
+            }
+
             // Get references separately to avoid double borrow
             let state_opt = buffers.get_mut(&buffer_id);
             let event_log_opt = event_logs.get_mut(&buffer_id);
@@ -796,6 +900,36 @@ impl SplitRenderer {
                     split_id,
                     is_active,
                 );
+
+                // Data files get a line with the key path at the cursor
+                // above their content
+                let data_path = data_path_style.and_then(|style| {
+                    let position = state.cursors.primary().position;
+                    let outline = state.data_outline.get(&state.buffer)?;
+                    Some(format_path(&outline.path_at(position), style))
+                });
+                // Data files with a key path get a winbar either way
+                if (show_winbar || data_path.is_some()) && layout.content_rect.height > 1 {
+                    let winbar_rect =
+                        Rect::new(split_area.x, layout.content_rect.y, split_area.width, 1);
+                    let segments = split_view_states
+                        .as_deref()
+                        .and_then(|vs| vs.get(&split_id))
+                        .map_or(&[][..], |vs| vs.winbar_segments.as_slice());
+                    let winbar = Winbar {
+                        name: Self::buffer_name(state, buffer_metadata.get(&buffer_id)),
+                        modified: state.buffer.is_modified(),
+                        breadcrumb: data_path.as_deref(),
+                        segments,
+                    };
+                    winbar.render(frame, winbar_rect, theme, is_active);
+                    winbar_areas.push((split_id, buffer_id, winbar_rect));
+                    for rect in [&mut layout.content_rect, &mut layout.scrollbar_rect] {
+                        rect.y += 1;
+                        rect.height -= 1;
+                    }
+                }
+
                 Self::sync_viewport_to_content(
                     &mut viewport,
                     &mut state.buffer,
@@ -804,6 +938,24 @@ impl SplitRenderer {
                 );
                 let view_prefs =
                     Self::resolve_view_preferences(state, split_view_states.as_deref(), split_id);
+                // Compose mode draws its own column guides; panels such as
+                // diagnostics have no line length to guide
+                let is_virtual = buffer_metadata
+                    .get(&buffer_id)
+                    .is_some_and(|metadata| metadata.is_virtual());
+                let buffer_rulers = if view_prefs.view_mode == ViewMode::Compose || is_virtual {
+                    Vec::new()
+                } else {
+                    state.rulers.as_deref().unwrap_or(rulers).to_vec()
+                };
+
+                // The layout cache lives on the split; take it for the render
+                // and hand it back with the viewport below
+                let mut layout_cache = split_view_states
+                    .as_deref_mut()
+                    .and_then(|vs| vs.get_mut(&split_id))
+                    .map(|vs| std::mem::take(&mut vs.line_layout))
+                    .unwrap_or_default();
 
                 let split_view_mappings = Self::render_buffer_in_split(
                     frame,
@@ -825,6 +977,13 @@ impl SplitRenderer {
                     buffer_id,
                     hide_cursor,
                     relative_line_numbers,
+                    render_whitespace,
+                    &buffer_rulers,
+                    highlight_overlength,
+                    contrast,
+                    &mut layout_cache,
+                    &search_namespace(split_id),
+                    now,
                 );
 
                 // Store view line mappings for mouse click handling
@@ -868,6 +1027,7 @@ impl SplitRenderer {
                             viewport.should_skip_ensure_visible()
                         );
                         view_state.viewport = viewport.clone();
+                        view_state.line_layout = layout_cache;
                     }
                 }
 
@@ -895,9 +1055,24 @@ impl SplitRenderer {
             close_split_areas,
             maximize_split_areas,
             view_line_mappings,
+            winbar_areas,
         )
     }
 
+    /// Name of a buffer as its tab shows it
+    fn buffer_name<'a>(state: &'a EditorState, metadata: Option<&'a BufferMetadata>) -> &'a str {
+        let is_terminal = metadata.and_then(|m| m.virtual_mode()) == Some("terminal");
+        let file_name = state
+            .buffer
+            .file_path()
+            .and_then(|p| p.file_name())
+            .and_then(|n| n.to_str())
+            .filter(|_| !is_terminal);
+        file_name
+            .or_else(|| metadata.map(|m| m.display_name.as_str()))
+            .unwrap_or("[No Name]")
+    }
+
     /// Render a split separator line
     fn render_separator(
         frame: &mut Frame,
@@ -911,7 +1086,7 @@ impl SplitRenderer {
             SplitDirection::Horizontal => {
                 // Draw horizontal line
                 let line_area = Rect::new(x, y, length, 1);
-                let line_text = "─".repeat(length as usize);
+                let line_text = glyphs().border_horizontal.repeat(length as usize);
                 let paragraph =
                     Paragraph::new(line_text).style(Style::default().fg(theme.split_separator_fg));
                 frame.render_widget(paragraph, line_area);
@@ -920,16 +1095,16 @@ impl SplitRenderer {
                 // Draw vertical line
                 for offset in 0..length {
                     let cell_area = Rect::new(x, y + offset, 1, 1);
-                    let paragraph =
-                        Paragraph::new("│").style(Style::default().fg(theme.split_separator_fg));
+                    let paragraph = Paragraph::new(glyphs().border_vertical)
+                        .style(Style::default().fg(theme.split_separator_fg));
                     frame.render_widget(paragraph, cell_area);
                 }
             }
         }
     }
 
-    fn split_layout(split_area: Rect) -> SplitLayout {
-        let tabs_height = 1u16;
+    fn split_layout(split_area: Rect, hide_tabs: bool) -> SplitLayout {
+        let tabs_height = if hide_tabs { 0 } else { 1u16 };
         let scrollbar_width = 1u16;
 
         let tabs_rect = Rect::new(split_area.x, split_area.y, split_area.width, tabs_height);
@@ -1198,11 +1373,42 @@ impl SplitRenderer {
         line_wrap_enabled: bool,
         content_width: usize,
         gutter_width: usize,
+        layout_cache: Option<(&mut LineLayoutCache, LayoutParams)>,
+        theme: &crate::view::theme::Theme,
     ) -> ViewData {
         // Check if buffer is binary before building tokens
         let is_binary = state.buffer.is_binary();
         let line_ending = state.buffer.line_ending();
 
+        // Wrapping is always applied for safety, but with different thresholds.
+        // When line_wrap is on: wrap at viewport width for normal text flow.
+        // When line_wrap is off: wrap at MAX_SAFE_LINE_WIDTH to prevent memory exhaustion
+        // from extremely long lines (e.g., 10MB single-line JSON files).
+        let effective_width = if line_wrap_enabled {
+            content_width
+        } else {
+            MAX_SAFE_LINE_WIDTH
+        };
+
+        // Plain source text is laid out line by line so unchanged lines can be
+        // reused from the previous render. Plugin transforms and binary views
+        // see the whole token stream and always take the full path.
+        if let (None, false, Some((cache, params))) = (&view_transform, is_binary, layout_cache) {
+            cache.begin_pass(params);
+            let source_lines = Self::build_cached_source_lines(
+                state,
+                cache,
+                viewport.top_byte,
+                estimated_line_length,
+                visible_count,
+                effective_width,
+                gutter_width,
+            );
+            cache.end_pass();
+            let lines = Self::inject_virtual_lines(source_lines, state, theme);
+            return ViewData { lines };
+        }
+
         // Build base token stream from source
         let base_tokens = Self::build_base_tokens(
             &mut state.buffer,
@@ -1215,16 +1421,6 @@ impl SplitRenderer {
 
         // Use plugin transform if available, otherwise use base tokens
         let mut tokens = view_transform.map(|vt| vt.tokens).unwrap_or(base_tokens);
-
-        // Apply wrapping transform - always enabled for safety, but with different thresholds.
-        // When line_wrap is on: wrap at viewport width for normal text flow.
-        // When line_wrap is off: wrap at MAX_SAFE_LINE_WIDTH to prevent memory exhaustion
-        // from extremely long lines (e.g., 10MB single-line JSON files).
-        let effective_width = if line_wrap_enabled {
-            content_width
-        } else {
-            MAX_SAFE_LINE_WIDTH
-        };
         tokens = Self::apply_wrapping_transform(tokens, effective_width, gutter_width);
 
         // Convert tokens to display lines using the view pipeline
@@ -1237,11 +1433,75 @@ impl SplitRenderer {
             ViewLineIterator::new(&tokens, is_binary, ansi_aware, state.tab_size).collect();
 
         // Inject virtual lines (LineAbove/LineBelow) from VirtualTextManager
-        let lines = Self::inject_virtual_lines(source_lines, state);
+        let lines = Self::inject_virtual_lines(source_lines, state, theme);
 
         ViewData { lines }
     }
 
+    /// Lay out the visible source lines one at a time, reusing cached layouts
+    /// for lines whose text is unchanged since the last pass
+    fn build_cached_source_lines(
+        state: &mut EditorState,
+        cache: &mut LineLayoutCache,
+        top_byte: usize,
+        estimated_line_length: usize,
+        visible_count: usize,
+        effective_width: usize,
+        gutter_width: usize,
+    ) -> Vec<ViewLine> {
+        let line_ending = state.buffer.line_ending();
+        let tab_size = state.tab_size;
+        let max_lines = visible_count.saturating_add(4);
+
+        let mut lines = Vec::new();
+        let mut lines_seen = 0usize;
+        let mut iter = state.buffer.line_iterator(top_byte, estimated_line_length);
+
+        while lines_seen < max_lines {
+            let Some((line_start, line_content)) = iter.next() else {
+                break;
+            };
+            let first = lines_seen == 0;
+            let key = LineLayoutCache::key(&line_content);
+
+            if let Some((rows, cached)) = cache.get(key, line_start, first) {
+                if lines_seen + rows <= max_lines {
+                    lines_seen += rows;
+                    lines.extend(cached);
+                    continue;
+                }
+            }
+
+            let rows_before = lines_seen;
+            let mut tokens = Vec::new();
+            let complete = Self::push_line_tokens(
+                &mut tokens,
+                line_start,
+                &line_content,
+                line_ending,
+                &mut lines_seen,
+                max_lines,
+            );
+            let tokens = Self::apply_wrapping_transform(tokens, effective_width, gutter_width);
+            let mut laid_out: Vec<ViewLine> =
+                ViewLineIterator::new(&tokens, false, true, tab_size).collect();
+            if !first {
+                if let Some(line) = laid_out.first_mut() {
+                    line.line_start = LineStart::AfterSourceNewline;
+                }
+            }
+
+            if complete {
+                cache.insert(key, line_start, lines_seen - rows_before, &laid_out);
+            } else {
+                cache.record_uncached();
+            }
+            lines.extend(laid_out);
+        }
+
+        lines
+    }
+
     /// Create a ViewLine from virtual text content (for LineAbove/LineBelow)
     fn create_virtual_line(text: &str, style: ratatui::style::Style) -> ViewLine {
         use crate::services::plugins::api::ViewTokenStyle;
@@ -1283,7 +1543,11 @@ impl SplitRenderer {
     }
 
     /// Inject virtual lines (LineAbove/LineBelow) into the ViewLine stream
-    fn inject_virtual_lines(source_lines: Vec<ViewLine>, state: &EditorState) -> Vec<ViewLine> {
+    fn inject_virtual_lines(
+        source_lines: Vec<ViewLine>,
+        state: &EditorState,
+        theme: &crate::view::theme::Theme,
+    ) -> Vec<ViewLine> {
         use crate::view::virtual_text::VirtualTextPosition;
 
         // Get viewport byte range from source lines
@@ -1327,7 +1591,10 @@ impl SplitRenderer {
                 for (anchor_pos, vtext) in &virtual_lines {
                     if *anchor_pos >= start && *anchor_pos < end {
                         if vtext.position == VirtualTextPosition::LineAbove {
-                            result.push(Self::create_virtual_line(&vtext.text, vtext.style));
+                            result.push(Self::create_virtual_line(
+                                &vtext.text,
+                                vtext.resolved_style(theme),
+                            ));
                         }
                     }
                 }
@@ -1341,7 +1608,10 @@ impl SplitRenderer {
                 for (anchor_pos, vtext) in &virtual_lines {
                     if *anchor_pos >= start && *anchor_pos < end {
                         if vtext.position == VirtualTextPosition::LineBelow {
-                            result.push(Self::create_virtual_line(&vtext.text, vtext.style));
+                            result.push(Self::create_virtual_line(
+                                &vtext.text,
+                                vtext.resolved_style(theme),
+                            ));
                         }
                     }
                 }
@@ -1359,7 +1629,6 @@ impl SplitRenderer {
         is_binary: bool,
         line_ending: crate::model::buffer::LineEnding,
     ) -> Vec<crate::services::plugins::api::ViewTokenWire> {
-        use crate::model::buffer::LineEnding;
         use crate::services::plugins::api::{ViewTokenWire, ViewTokenWireKind};
 
         let mut tokens = Vec::new();
@@ -1381,116 +1650,14 @@ impl SplitRenderer {
 
         while lines_seen < max_lines {
             if let Some((line_start, line_content)) = iter.next() {
-                let mut byte_offset = 0usize;
-                let content_bytes = line_content.as_bytes();
-                let mut skip_next_lf = false; // Track if we should skip \n after \r in CRLF
-                let mut chars_this_line = 0usize; // Track chars to enforce MAX_SAFE_LINE_WIDTH
-                for ch in line_content.chars() {
-                    // Limit characters per line to prevent memory exhaustion from huge lines.
-                    // Insert a Break token to force wrapping at safe intervals.
-                    if chars_this_line >= MAX_SAFE_LINE_WIDTH {
-                        tokens.push(ViewTokenWire {
-                            source_offset: None,
-                            kind: ViewTokenWireKind::Break,
-                            style: None,
-                        });
-                        chars_this_line = 0;
-                        // Count this as a new visual line for the max_lines limit
-                        lines_seen += 1;
-                        if lines_seen >= max_lines {
-                            break;
-                        }
-                    }
-                    chars_this_line += 1;
-
-                    let ch_len = ch.len_utf8();
-                    let source_offset = Some(line_start + byte_offset);
-
-                    match ch {
-                        '\r' => {
-                            // In CRLF mode with \r\n: emit Newline at \r position, skip the \n
-                            // This allows cursor at \r (end of line) to be visible
-                            // In LF/Unix files, ANY \r is unusual and should be shown as <0D>
-                            let is_crlf_file = line_ending == LineEnding::CRLF;
-                            let next_byte = content_bytes.get(byte_offset + 1);
-                            if is_crlf_file && next_byte == Some(&b'\n') {
-                                // CRLF: emit Newline token at \r position for cursor visibility
-                                tokens.push(ViewTokenWire {
-                                    source_offset,
-                                    kind: ViewTokenWireKind::Newline,
-                                    style: None,
-                                });
-                                // Mark to skip the following \n in the char iterator
-                                skip_next_lf = true;
-                                byte_offset += ch_len;
-                                continue;
-                            }
-                            // LF file or standalone \r - show as control character
-                            tokens.push(ViewTokenWire {
-                                source_offset,
-                                kind: ViewTokenWireKind::BinaryByte(ch as u8),
-                                style: None,
-                            });
-                        }
-                        '\n' if skip_next_lf => {
-                            // Skip \n that follows \r in CRLF mode (already emitted Newline at \r)
-                            skip_next_lf = false;
-                            byte_offset += ch_len;
-                            continue;
-                        }
-                        '\n' => {
-                            tokens.push(ViewTokenWire {
-                                source_offset,
-                                kind: ViewTokenWireKind::Newline,
-                                style: None,
-                            });
-                        }
-                        ' ' => {
-                            tokens.push(ViewTokenWire {
-                                source_offset,
-                                kind: ViewTokenWireKind::Space,
-                                style: None,
-                            });
-                        }
-                        '\t' => {
-                            // Tab is safe, emit as Text
-                            tokens.push(ViewTokenWire {
-                                source_offset,
-                                kind: ViewTokenWireKind::Text(ch.to_string()),
-                                style: None,
-                            });
-                        }
-                        _ if Self::is_control_char(ch) => {
-                            // Control character - emit as BinaryByte to render as <XX>
-                            tokens.push(ViewTokenWire {
-                                source_offset,
-                                kind: ViewTokenWireKind::BinaryByte(ch as u8),
-                                style: None,
-                            });
-                        }
-                        _ => {
-                            // Accumulate consecutive non-space/non-newline chars into Text tokens
-                            if let Some(last) = tokens.last_mut() {
-                                if let ViewTokenWireKind::Text(ref mut s) = last.kind {
-                                    // Extend existing Text token if contiguous
-                                    let expected_offset = last.source_offset.map(|o| o + s.len());
-                                    if expected_offset == Some(line_start + byte_offset) {
-                                        s.push(ch);
-                                        byte_offset += ch_len;
-                                        continue;
-                                    }
-                                }
-                            }
-                            tokens.push(ViewTokenWire {
-                                source_offset,
-                                kind: ViewTokenWireKind::Text(ch.to_string()),
-                                style: None,
-                            });
-                        }
-                    }
-                    byte_offset += ch_len;
-                }
-                lines_seen += 1;
+                Self::push_line_tokens(
+                    &mut tokens,
+                    line_start,
+                    &line_content,
+                    line_ending,
+                    &mut lines_seen,
+                    max_lines,
+                );
             } else {
                 break;
             }
@@ -1508,6 +1675,144 @@ impl SplitRenderer {
         tokens
     }
 
+    /// Append the tokens for one source line starting at byte `line_start`.
+    ///
+    /// `lines_seen` counts emitted rows (including forced breaks for huge
+    /// lines) against `max_lines`. Returns false if the line was cut short
+    /// because the row budget ran out.
+    fn push_line_tokens(
+        tokens: &mut Vec<crate::services::plugins::api::ViewTokenWire>,
+        line_start: usize,
+        line_content: &str,
+        line_ending: crate::model::buffer::LineEnding,
+        lines_seen: &mut usize,
+        max_lines: usize,
+    ) -> bool {
+        use crate::model::buffer::LineEnding;
+        use crate::model::encoding::placeholder_byte;
+        use crate::services::plugins::api::{ViewTokenWire, ViewTokenWireKind};
+
+        let mut byte_offset = 0usize;
+        let content_bytes = line_content.as_bytes();
+        let mut skip_next_lf = false; // Track if we should skip \n after \r in CRLF
+        let mut chars_this_line = 0usize; // Track chars to enforce MAX_SAFE_LINE_WIDTH
+        for ch in line_content.chars() {
+            // Limit characters per line to prevent memory exhaustion from huge lines.
+            // Insert a Break token to force wrapping at safe intervals.
+            if chars_this_line >= MAX_SAFE_LINE_WIDTH {
+                tokens.push(ViewTokenWire {
+                    source_offset: None,
+                    kind: ViewTokenWireKind::Break,
+                    style: None,
+                });
+                chars_this_line = 0;
+                // Count this as a new visual line for the max_lines limit
+                *lines_seen += 1;
+                if *lines_seen >= max_lines {
+                    return false;
+                }
+            }
+            chars_this_line += 1;
+
+            let ch_len = ch.len_utf8();
+            let source_offset = Some(line_start + byte_offset);
+
+            match ch {
+                '\r' => {
+                    // In CRLF mode with \r\n: emit Newline at \r position, skip the \n
+                    // This allows cursor at \r (end of line) to be visible
+                    // In LF/Unix files, ANY \r is unusual and should be shown as <0D>
+                    let is_crlf_file = line_ending == LineEnding::CRLF;
+                    let next_byte = content_bytes.get(byte_offset + 1);
+                    if is_crlf_file && next_byte == Some(&b'\n') {
+                        // CRLF: emit Newline token at \r position for cursor visibility
+                        tokens.push(ViewTokenWire {
+                            source_offset,
+                            kind: ViewTokenWireKind::Newline,
+                            style: None,
+                        });
+                        // Mark to skip the following \n in the char iterator
+                        skip_next_lf = true;
+                        byte_offset += ch_len;
+                        continue;
+                    }
+                    // LF file or standalone \r - show as control character
+                    tokens.push(ViewTokenWire {
+                        source_offset,
+                        kind: ViewTokenWireKind::BinaryByte(ch as u8),
+                        style: None,
+                    });
+                }
+                '\n' if skip_next_lf => {
+                    // Skip \n that follows \r in CRLF mode (already emitted Newline at \r)
+                    skip_next_lf = false;
+                    byte_offset += ch_len;
+                    continue;
+                }
+                '\n' => {
+                    tokens.push(ViewTokenWire {
+                        source_offset,
+                        kind: ViewTokenWireKind::Newline,
+                        style: None,
+                    });
+                }
+                ' ' => {
+                    tokens.push(ViewTokenWire {
+                        source_offset,
+                        kind: ViewTokenWireKind::Space,
+                        style: None,
+                    });
+                }
+                '\t' => {
+                    // Tab is safe, emit as Text
+                    tokens.push(ViewTokenWire {
+                        source_offset,
+                        kind: ViewTokenWireKind::Text(ch.to_string()),
+                        style: None,
+                    });
+                }
+                _ if Self::is_control_char(ch) => {
+                    // Control character - emit as BinaryByte to render as <XX>
+                    tokens.push(ViewTokenWire {
+                        source_offset,
+                        kind: ViewTokenWireKind::BinaryByte(ch as u8),
+                        style: None,
+                    });
+                }
+                _ if placeholder_byte(ch).is_some() => {
+                    // Invalid byte of the file - render it as <XX> too
+                    tokens.push(ViewTokenWire {
+                        source_offset,
+                        kind: ViewTokenWireKind::BinaryByte(placeholder_byte(ch).unwrap_or(0)),
+                        style: None,
+                    });
+                }
+                _ => {
+                    // Accumulate consecutive non-space/non-newline chars into Text tokens
+                    if let Some(last) = tokens.last_mut() {
+                        if let ViewTokenWireKind::Text(ref mut s) = last.kind {
+                            // Extend existing Text token if contiguous
+                            let expected_offset = last.source_offset.map(|o| o + s.len());
+                            if expected_offset == Some(line_start + byte_offset) {
+                                s.push(ch);
+                                byte_offset += ch_len;
+                                continue;
+                            }
+                        }
+                    }
+                    tokens.push(ViewTokenWire {
+                        source_offset,
+                        kind: ViewTokenWireKind::Text(ch.to_string()),
+                        style: None,
+                    });
+                }
+            }
+            byte_offset += ch_len;
+        }
+        *lines_seen += 1;
+        true
+    }
+
     /// Build tokens for binary files by reading raw bytes directly
     /// This preserves byte values >= 0x80 that would be lost by String::from_utf8_lossy
     fn build_base_tokens_binary(
@@ -1701,8 +2006,10 @@ impl SplitRenderer {
         let mut wrapped = Vec::new();
         let mut current_line_width = 0;
 
-        // Calculate available width (accounting for gutter on first line only)
-        let available_width = content_width.saturating_sub(gutter_width);
+        // Calculate available width (accounting for gutter on first line only).
+        // A split no wider than its gutter still takes a grapheme per row, or
+        // the splitting below would only ever emit breaks
+        let available_width = content_width.saturating_sub(gutter_width).max(1);
 
         for token in tokens {
             match &token.kind {
@@ -1942,6 +2249,40 @@ impl SplitRenderer {
         }
     }
 
+    /// Tint the ruler columns (`ui.rulers`) over the full height of the area
+    ///
+    /// Rulers are the bottom layer: only cells still showing the editor
+    /// background are tinted, so selections, search matches, diagnostics and
+    /// overlength text keep their own background.
+    fn render_rulers(
+        frame: &mut Frame,
+        render_area: Rect,
+        gutter_width: usize,
+        left_column: usize,
+        rulers: &[u16],
+        theme: &crate::view::theme::Theme,
+    ) {
+        let text_x = render_area.x as usize + gutter_width;
+        let right = (render_area.x + render_area.width) as usize;
+        let buf = frame.buffer_mut();
+        for &col in rulers {
+            let Some(offset) = (col as usize).checked_sub(left_column) else {
+                continue;
+            };
+            let x = text_x + offset;
+            if x >= right {
+                continue;
+            }
+            for y in render_area.y..render_area.y + render_area.height {
+                if let Some(cell) = buf.cell_mut((x as u16, y)) {
+                    if cell.bg == theme.editor_bg {
+                        cell.set_bg(theme.ruler_bg);
+                    }
+                }
+            }
+        }
+    }
+
     fn render_compose_margins(
         frame: &mut Frame,
         area: Rect,
@@ -2057,6 +2398,8 @@ impl SplitRenderer {
         primary_cursor_position: usize,
         theme: &crate::view::theme::Theme,
         highlight_context_bytes: usize,
+        search_namespace: &OverlayNamespace,
+        now: Instant,
     ) -> DecorationContext {
         // Extend highlighting range by ~1 viewport size before/after for better context.
         // This helps tree-sitter parse multi-line constructs that span viewport boundaries.
@@ -2085,6 +2428,7 @@ impl SplitRenderer {
                 viewport_end,
                 highlight_context_bytes,
                 theme.semantic_highlight_bg,
+                now,
             )
             .to_vec();
 
@@ -2092,7 +2436,14 @@ impl SplitRenderer {
             .overlays
             .query_viewport(viewport_start, viewport_end, &state.marker_list)
             .into_iter()
-            .map(|(overlay, range)| (overlay.clone(), range))
+            // Search highlights of other splits showing this buffer
+            .filter(|(overlay, _)| {
+                overlay
+                    .namespace
+                    .as_ref()
+                    .is_none_or(|ns| !is_search_namespace(ns) || ns == search_namespace)
+            })
+            .map(|(overlay, range)| (overlay.resolved(theme), range))
             .collect::<Vec<_>>();
 
         // Use the lsp-diagnostic namespace to identify diagnostic overlays
@@ -2112,15 +2463,43 @@ impl SplitRenderer {
                 .virtual_texts
                 .build_lookup(&state.marker_list, viewport_start, viewport_end)
                 .into_iter()
-                .map(|(position, texts)| (position, texts.into_iter().cloned().collect()))
+                .map(|(position, texts)| {
+                    let texts = texts
+                        .into_iter()
+                        .map(|text| crate::view::virtual_text::VirtualText {
+                            style: text.resolved_style(theme),
+                            ..text.clone()
+                        })
+                        .collect();
+                    (position, texts)
+                })
                 .collect();
 
         // Pre-compute line indicators for the viewport (only query markers in visible range)
-        let line_indicators = state.margins.get_indicators_for_viewport(
-            viewport_start,
-            viewport_end,
-            |byte_offset| state.buffer.get_line_number(byte_offset),
-        );
+        let line_indicators = state
+            .margins
+            .get_indicators_for_viewport(viewport_start, viewport_end, |byte_offset| {
+                state.buffer.get_line_number(byte_offset)
+            })
+            .into_iter()
+            .map(|(line, indicator)| (line, indicator.resolved(theme)))
+            .collect();
+        let plugin_column_indicators = state
+            .margins
+            .get_plugin_column_indicators_for_viewport(
+                viewport_start,
+                viewport_end,
+                |byte_offset| state.buffer.get_line_number(byte_offset),
+            )
+            .into_iter()
+            .map(|(column, by_line)| {
+                let by_line = by_line
+                    .into_iter()
+                    .map(|(line, indicator)| (line, indicator.resolved(theme)))
+                    .collect();
+                (column, by_line)
+            })
+            .collect();
 
         DecorationContext {
             highlight_spans,
@@ -2129,6 +2508,7 @@ impl SplitRenderer {
             virtual_text_lookup,
             diagnostic_lines,
             line_indicators,
+            plugin_column_indicators,
         }
     }
 
@@ -2170,6 +2550,9 @@ impl SplitRenderer {
             estimated_lines,
             left_column,
             relative_line_numbers,
+            render_whitespace,
+            overlength_column,
+            mut contrast,
         } = input;
 
         let selection_ranges = &selection.ranges;
@@ -2186,6 +2569,8 @@ impl SplitRenderer {
         let virtual_text_lookup = &decorations.virtual_text_lookup;
         let diagnostic_lines = &decorations.diagnostic_lines;
         let line_indicators = &decorations.line_indicators;
+        let plugin_column_indicators = &decorations.plugin_column_indicators;
+        let glyphs = glyphs();
 
         let mut lines = Vec::new();
         let mut lines_rendered = 0usize;
@@ -2206,6 +2591,10 @@ impl SplitRenderer {
         // Track whether the previous line was a source line (showed a line number)
         // Used to determine when to increment the line counter
         let mut prev_was_source_line = false;
+        // Source column where the current view line starts: wrapped
+        // continuations carry on from the end of the row before
+        let mut source_col_start = 0usize;
+        let mut prev_line_width = 0usize;
 
         loop {
             // Get the current ViewLine from the pipeline
@@ -2235,7 +2624,13 @@ impl SplitRenderer {
             let line_char_styles = &current_view_line.char_styles;
             let line_visual_to_char = &current_view_line.visual_to_char;
             let line_tab_starts = &current_view_line.tab_starts;
+            let line_marks = char_marks(current_view_line, render_whitespace);
             let _line_start_type = current_view_line.line_start; // Available for future use
+            if current_view_line.line_start.is_continuation() {
+                source_col_start += prev_line_width;
+            } else {
+                source_col_start = 0;
+            }
 
             // Helper to get source byte at a visual column using the new O(1) lookup
             let _source_byte_at_col = |vis_col: usize| -> Option<usize> {
@@ -2294,6 +2689,7 @@ impl SplitRenderer {
                     estimated_lines,
                     diagnostic_lines,
                     line_indicators,
+                    plugin_column_indicators,
                     cursor_line,
                     relative_line_numbers,
                 },
@@ -2444,7 +2840,7 @@ impl SplitRenderer {
                         .get(display_char_idx)
                         .and_then(|s| s.as_ref());
                     let CharStyleOutput {
-                        style,
+                        mut style,
                         is_secondary_cursor,
                     } = compute_char_style(&CharStyleContext {
                         byte_pos,
@@ -2460,11 +2856,46 @@ impl SplitRenderer {
                         is_active,
                     });
 
+                    // Whitespace inside a selection is drawn whatever the setting
+                    let mark = line_marks.get(display_char_idx).copied().flatten();
+                    let whitespace_symbol = mark
+                        .filter(|m| m.shown || (is_selected && m.is_whitespace()))
+                        .and_then(|m| symbol_for(&m, ch, &theme.whitespace));
+                    if let Some(mark) = mark.filter(|_| !is_cursor) {
+                        let drawn = whitespace_symbol.is_some()
+                            || mark.kind == MarkKind::Control
+                            || (mark.kind == MarkKind::TabFill && (mark.shown || is_selected));
+                        style = whitespace_style(
+                            style,
+                            mark,
+                            drawn,
+                            is_selected,
+                            render_whitespace,
+                            theme,
+                        );
+                    }
+
+                    // Text past the last ruler is tinted below every other
+                    // background (selection, search matches, diagnostics)
+                    if overlength_column.is_some_and(|limit| source_col_start + col_offset >= limit)
+                        && style.bg.is_none()
+                        && !is_cursor
+                    {
+                        style = style.bg(theme.overlength_bg);
+                    }
+
+                    // `ui.minimum_contrast` applies to text alone, against
+                    // whatever it ends up drawn on
+                    if let (Some(contrast), Some(fg)) = (contrast.as_deref_mut(), style.fg) {
+                        style =
+                            style.fg(contrast.foreground(fg, style.bg.unwrap_or(theme.editor_bg)));
+                    }
+
                     // Determine display character (tabs already expanded in ViewLineIterator)
                     // Show tab indicator (→) at the start of tab expansions (if enabled for this language)
                     let tab_indicator: String;
                     let display_char: &str = if is_cursor && lsp_waiting && is_active {
-                        "⋯"
+                        glyphs.waiting
                     } else if debug_tracker.is_some() && ch == '\r' {
                         // Debug mode: show CR explicitly
                         "\\r"
@@ -2475,9 +2906,12 @@ impl SplitRenderer {
                         ""
                     } else if ch == '\n' {
                         ""
+                    } else if let Some(symbol) = whitespace_symbol {
+                        tab_indicator = symbol;
+                        &tab_indicator
                     } else if is_tab_start && state.show_whitespace_tabs {
                         // Visual indicator for tab: show → at the first position
-                        tab_indicator = "→".to_string();
+                        tab_indicator = glyphs.tab.to_string();
                         &tab_indicator
                     } else {
                         tab_indicator = ch.to_string();
@@ -2616,6 +3050,7 @@ impl SplitRenderer {
 
             // Flush any remaining accumulated text at end of line
             span_acc.flush(&mut line_spans, &mut line_view_map);
+            prev_line_width = col_offset;
 
             // Set last_seg_y early so cursor detection works for both empty and non-empty lines
             // For lines without wrapping, this will be the final y position
@@ -2743,7 +3178,7 @@ impl SplitRenderer {
                             })
                             .max_by_key(|(o, _)| o.priority)
                             .and_then(|(overlay, _)| {
-                                match &overlay.face {
+                                match &overlay.face() {
                                     crate::view::overlay::OverlayFace::Background { color } => {
                                         // Set both fg and bg to ensure ANSI codes are output
                                         Some(Style::default().fg(*color).bg(*color))
@@ -2835,31 +3270,23 @@ impl SplitRenderer {
                 let mut implicit_line_spans = Vec::new();
                 let implicit_line_num = current_source_line_num + 1;
 
-                if state.margins.left_config.enabled {
-                    // Indicator column (space)
-                    implicit_line_spans.push(Span::styled(" ", Style::default()));
-
-                    // Line number
-                    let estimated_lines = (state.buffer.len() / 80).max(1);
-                    let margin_content = state.margins.render_line(
-                        implicit_line_num,
-                        crate::view::margin::MarginPosition::Left,
-                        estimated_lines,
-                    );
-                    let (rendered_text, style_opt) =
-                        margin_content.render(state.margins.left_config.width);
-                    let margin_style =
-                        style_opt.unwrap_or_else(|| Style::default().fg(theme.line_number_fg));
-                    implicit_line_spans.push(Span::styled(rendered_text, margin_style));
-
-                    // Separator
-                    if state.margins.left_config.show_separator {
-                        implicit_line_spans.push(Span::styled(
-                            state.margins.left_config.separator.to_string(),
-                            Style::default().fg(theme.line_number_fg),
-                        ));
-                    }
-                }
+                // The line has no indicators; only its number is drawn
+                render_left_margin(
+                    &LeftMarginContext {
+                        state,
+                        theme,
+                        is_continuation: false,
+                        current_source_line_num: implicit_line_num,
+                        estimated_lines: (state.buffer.len() / 80).max(1),
+                        diagnostic_lines: &HashSet::new(),
+                        line_indicators: &BTreeMap::new(),
+                        plugin_column_indicators: &HashMap::new(),
+                        cursor_line: implicit_line_num,
+                        relative_line_numbers: false,
+                    },
+                    &mut implicit_line_spans,
+                    &mut Vec::new(),
+                );
 
                 let implicit_y = lines.len() as u16;
                 lines.push(Line::from(implicit_line_spans));
@@ -2950,32 +3377,63 @@ impl SplitRenderer {
         view_transform: Option<ViewTransformPayload>,
         estimated_line_length: usize,
         highlight_context_bytes: usize,
-        _buffer_id: BufferId,
+        buffer_id: BufferId,
         hide_cursor: bool,
         relative_line_numbers: bool,
+        render_whitespace: WhitespaceRendering,
+        rulers: &[u16],
+        highlight_overlength: bool,
+        contrast: &mut ContrastCache,
+        layout_cache: &mut LineLayoutCache,
+        search_namespace: &OverlayNamespace,
+        now: Instant,
     ) -> Vec<ViewLineMapping> {
         let _span = tracing::trace_span!("render_buffer_in_split").entered();
 
         let line_wrap = viewport.line_wrap_enabled;
 
-        let overlay_count = state.overlays.all().len();
+        let overlay_count = state.overlays.len();
         if overlay_count > 0 {
             tracing::trace!("render_content: {} overlays present", overlay_count);
         }
 
         let visible_count = viewport.visible_line_count();
 
+        // Size the line numbers by the line count when it's known, so no
+        // digits are cut off; estimate it otherwise
         let buffer_len = state.buffer.len();
-        let estimated_lines = (buffer_len / 80).max(1);
+        let estimated_lines = state
+            .buffer
+            .line_count()
+            .unwrap_or((buffer_len / 80).max(1));
         state.margins.update_width_for_buffer(estimated_lines);
-        let gutter_width = state.margins.left_total_width();
 
         let compose_layout = Self::calculate_compose_layout(area, &view_mode, compose_width);
         let render_area = compose_layout.render_area;
+        // A split narrower than its gutter shows only (part of) the gutter
+        let gutter_width = state
+            .margins
+            .left_total_width()
+            .min(render_area.width as usize);
 
         // Clone view_transform so we can reuse it if scrolling triggers a rebuild
         let view_transform_for_rebuild = view_transform.clone();
 
+        let layout_params =
+            |state: &EditorState, viewport: &crate::view::viewport::Viewport| LayoutParams {
+                buffer_id,
+                theme: theme.name.clone(),
+                top_byte: viewport.top_byte,
+                viewport_width: viewport.width,
+                viewport_height: viewport.height,
+                line_wrap,
+                wrap_width: render_area.width as usize,
+                gutter_width,
+                tab_size: state.tab_size,
+                line_ending: state.buffer.line_ending(),
+            };
+
+        let params = layout_params(state, viewport);
         let view_data = Self::build_view_data(
             state,
             viewport,
@@ -2985,6 +3443,8 @@ impl SplitRenderer {
             line_wrap,
             render_area.width as usize,
             gutter_width,
+            Some((&mut *layout_cache, params)),
+            theme,
         );
 
         // Ensure cursor is visible using Layout-aware check (handles virtual lines)
@@ -2995,6 +3455,7 @@ impl SplitRenderer {
         // If we scrolled, rebuild view_data from new position WITH the view_transform
         // This ensures virtual lines are included in the rebuilt view
         let view_data = if scrolled {
+            let params = layout_params(state, viewport);
             Self::build_view_data(
                 state,
                 viewport,
@@ -3004,6 +3465,8 @@ impl SplitRenderer {
                 line_wrap,
                 render_area.width as usize,
                 gutter_width,
+                Some((layout_cache, params)),
+                theme,
             )
         } else {
             view_data
@@ -3035,9 +3498,7 @@ impl SplitRenderer {
             );
         }
 
-        let starting_line_num = state
-            .buffer
-            .populate_line_cache(viewport.top_byte, visible_count);
+        let starting_line_num = state.buffer.get_line_number(viewport.top_byte);
 
         let viewport_start = viewport.top_byte;
         let viewport_end = Self::calculate_viewport_end(
@@ -3054,6 +3515,8 @@ impl SplitRenderer {
             selection.primary_cursor_position,
             theme,
             highlight_context_bytes,
+            search_namespace,
+            now,
         );
 
         // Use top_view_line_offset to handle scrolling through virtual lines.
@@ -3110,6 +3573,13 @@ impl SplitRenderer {
             estimated_lines,
             left_column: viewport.left_column,
             relative_line_numbers,
+            render_whitespace,
+            overlength_column: rulers
+                .iter()
+                .max()
+                .filter(|_| highlight_overlength)
+                .map(|&col| col as usize),
+            contrast: Some(contrast),
         });
 
         let mut lines = render_output.lines;
@@ -3128,12 +3598,22 @@ impl SplitRenderer {
             );
         }
 
+        crate::view::ambiguous_width::pad_lines(&mut lines);
         frame.render_widget(Clear, render_area);
         let editor_block = Block::default()
             .borders(Borders::NONE)
             .style(Style::default().bg(theme.editor_bg));
         frame.render_widget(Paragraph::new(lines).block(editor_block), render_area);
 
+        Self::render_rulers(
+            frame,
+            render_area,
+            gutter_width,
+            viewport.left_column,
+            rulers,
+            theme,
+        );
+
         // Render column guides if present (for tables, etc.)
         if let Some(guides) = compose_column_guides {
             let guide_style = Style::default()
@@ -3151,7 +3631,7 @@ impl SplitRenderer {
                 if guide_x >= render_area.x && guide_x < render_area.x + render_area.width {
                     for row in 0..guide_height {
                         let cell_area = Rect::new(guide_x, render_area.y + row as u16, 1, 1);
-                        let guide_char = Paragraph::new("│").style(guide_style);
+                        let guide_char = Paragraph::new(glyphs().guide).style(guide_style);
                         frame.render_widget(guide_char, cell_area);
                     }
                 }
@@ -3374,10 +3854,27 @@ mod tests {
         content: &str,
         cursor_pos: usize,
         gutters_enabled: bool,
+    ) -> (LineRenderOutput, usize, bool, usize) {
+        render_output_with(
+            content,
+            cursor_pos,
+            None,
+            gutters_enabled,
+            WhitespaceRendering::None,
+        )
+    }
+
+    fn render_output_with(
+        content: &str,
+        cursor_pos: usize,
+        selection_anchor: Option<usize>,
+        gutters_enabled: bool,
+        render_whitespace: WhitespaceRendering,
     ) -> (LineRenderOutput, usize, bool, usize) {
         let mut state = EditorState::new(20, 6, 1024);
         state.buffer = Buffer::from_str(content, 1024);
         state.cursors.primary_mut().position = cursor_pos.min(state.buffer.len());
+        state.cursors.primary_mut().anchor = selection_anchor;
         // Create a standalone viewport (no longer part of EditorState)
         let viewport = Viewport::new(20, 4);
         // Enable/disable line numbers/gutters based on parameter
@@ -3386,6 +3883,7 @@ mod tests {
         let render_area = Rect::new(0, 0, 20, 4);
         let visible_count = viewport.visible_line_count();
         let gutter_width = state.margins.left_total_width();
+        let theme = Theme::default();
 
         let view_data = SplitRenderer::build_view_data(
             &mut state,
@@ -3396,17 +3894,20 @@ mod tests {
             false, // line wrap disabled for tests
             render_area.width as usize,
             gutter_width,
+            None,
+            &theme,
         );
         let view_anchor = SplitRenderer::calculate_view_anchor(&view_data.lines, 0);
 
-        let estimated_lines = (state.buffer.len() / 80).max(1);
+        let estimated_lines = state
+            .buffer
+            .line_count()
+            .unwrap_or((state.buffer.len() / 80).max(1));
         state.margins.update_width_for_buffer(estimated_lines);
         let gutter_width = state.margins.left_total_width();
 
         let selection = SplitRenderer::selection_context(&state);
-        let starting_line_num = state
-            .buffer
-            .populate_line_cache(viewport.top_byte, visible_count);
+        let starting_line_num = state.buffer.get_line_number(viewport.top_byte);
         let viewport_start = viewport.top_byte;
         let viewport_end = SplitRenderer::calculate_viewport_end(
             &mut state,
@@ -3414,7 +3915,6 @@ mod tests {
             content.len().max(1),
             visible_count,
         );
-        let theme = Theme::default();
         let decorations = SplitRenderer::decoration_context(
             &mut state,
             viewport_start,
@@ -3422,6 +3922,8 @@ mod tests {
             selection.primary_cursor_position,
             &theme,
             100_000, // default highlight context bytes
+            &search_namespace(crate::model::event::SplitId(0)),
+            Instant::now(),
         );
 
         let output = SplitRenderer::render_view_lines(LineRenderInput {
@@ -3441,6 +3943,9 @@ mod tests {
             estimated_lines,
             left_column: viewport.left_column,
             relative_line_numbers: false,
+            render_whitespace,
+            overlength_column: None,
+            contrast: None,
         });
 
         (
@@ -3505,6 +4010,73 @@ mod tests {
         assert_eq!(cursor, Some((3, 0)));
     }
 
+    /// Tab, single and double spaces, NBSP, form feed and trailing spaces
+    const WHITESPACE_SAMPLE: &str = "a\tb c  \u{a0}\x0c  \nz";
+
+    /// Characters and styles drawn for the first line, up to the width of
+    /// `expected`
+    fn first_line_cells(output: &LineRenderOutput, expected: &str) -> Vec<(char, Style)> {
+        output.lines[0]
+            .spans
+            .iter()
+            .flat_map(|span| span.content.chars().map(move |ch| (ch, span.style)))
+            .take(expected.chars().count())
+            .collect()
+    }
+
+    fn render_whitespace_sample(
+        mode: WhitespaceRendering,
+        selection_anchor: Option<usize>,
+    ) -> Vec<(char, Style)> {
+        let content = WHITESPACE_SAMPLE;
+        let (output, ..) =
+            render_output_with(content, content.len(), selection_anchor, false, mode);
+        first_line_cells(&output, "a→  b c  ⍽<0C>  ")
+    }
+
+    fn cells_text(cells: &[(char, Style)]) -> String {
+        cells.iter().map(|(ch, _)| *ch).collect()
+    }
+
+    #[test]
+    fn whitespace_rendering_per_mode() {
+        let expected = [
+            (WhitespaceRendering::None, "a→  b c  \u{a0}<0C>  "),
+            (WhitespaceRendering::Trailing, "a→  b c  ⍽<0C>··"),
+            (WhitespaceRendering::Boundary, "a→  b c··⍽<0C>··"),
+            (WhitespaceRendering::All, "a→  b·c··⍽<0C>··"),
+        ];
+        for (mode, text) in expected {
+            let cells = render_whitespace_sample(mode, None);
+            assert_eq!(cells_text(&cells), text, "{:?}", mode);
+        }
+    }
+
+    #[test]
+    fn whitespace_rendering_highlights_trailing_and_control() {
+        let theme = Theme::default();
+        let trailing_bg = Some(theme.whitespace.trailing_bg);
+
+        let cells = render_whitespace_sample(WhitespaceRendering::Trailing, None);
+        let (head, tail) = cells.split_at(cells.len() - 2);
+        assert!(tail.iter().all(|(_, style)| style.bg == trailing_bg));
+        assert!(head.iter().all(|(_, style)| style.bg != trailing_bg));
+        // The form feed placeholder sits between the NBSP and the trailing spaces
+        let control = &head[head.len() - 4..];
+        assert!(control
+            .iter()
+            .all(|(_, style)| style.fg == Some(theme.whitespace.control_fg)));
+
+        let cells = render_whitespace_sample(WhitespaceRendering::None, None);
+        assert!(cells.iter().all(|(_, style)| style.bg != trailing_bg));
+    }
+
+    #[test]
+    fn whitespace_rendering_shows_selected_whitespace() {
+        let cells = render_whitespace_sample(WhitespaceRendering::None, Some(0));
+        assert_eq!(cells_text(&cells), "a→  b·c··⍽<0C>··");
+    }
+
     // Helper to count all cursor positions in rendered output
     // Cursors can appear as:
     // 1. Primary cursor in output.cursor (hardware cursor position)
@@ -3,9 +3,13 @@
 use std::path::Path;
 
 use crate::app::WarningLevel;
-use crate::primitives::display_width::{char_width, str_width};
+use crate::primitives::display_width::str_width;
+use crate::primitives::selection_stats::SelectionSummary;
+use crate::primitives::truncate;
+use crate::services::lsp::diagnostic_store::SeverityCounts;
 use crate::state::EditorState;
-use crate::view::prompt::Prompt;
+use crate::view::glyphs::glyphs;
+use crate::view::prompt::{Prompt, PromptType};
 use ratatui::layout::Rect;
 use ratatui::style::{Modifier, Style};
 use ratatui::text::{Line, Span};
@@ -22,6 +26,10 @@ pub struct StatusBarLayout {
     pub warning_badge: Option<(u16, u16, u16)>,
     /// Line ending indicator area (row, start_col, end_col)
     pub line_ending_indicator: Option<(u16, u16, u16)>,
+    /// Blocked project commands badge area (row, start_col, end_col) - None if none blocked
+    pub blocked_commands_badge: Option<(u16, u16, u16)>,
+    /// Progress indicator area (row, start_col, end_col) - None if nothing in progress
+    pub progress_indicator: Option<(u16, u16, u16)>,
 }
 
 /// Status bar hover state for styling clickable indicators
@@ -248,6 +256,14 @@ impl StatusBarRenderer {
     /// * `update_available` - Optional new version string if an update is available
     /// * `warning_level` - LSP warning level (for coloring LSP indicator)
     /// * `general_warning_count` - Number of general warnings (for badge display)
+    /// * `blocked_command_count` - Number of project commands held back (for badge display)
+    /// * `data_path` - Key path at the cursor in a JSON, YAML or TOML file
+    /// * `input_locked` - Whether the focused split refuses typed edits
+    /// * `read_only` - Whether the buffer's file can't be written
+    /// * `protected` - Whether the buffer's file matches `files.protected_globs` and is locked
+    /// * `search_match` - Current match and match count of the focused split's search
+    /// * `progress` - Label of the single long operation in progress
+    /// * `diagnostics` - Diagnostic counts of the buffer's file
     ///
     /// # Returns
     /// Layout information with positions of clickable indicators
@@ -265,7 +281,15 @@ impl StatusBarRenderer {
         update_available: Option<&str>,
         warning_level: WarningLevel,
         general_warning_count: usize,
+        blocked_command_count: usize,
         hover: StatusBarHover,
+        data_path: Option<&str>,
+        input_locked: bool,
+        read_only: bool,
+        protected: bool,
+        search_match: Option<(usize, usize)>,
+        progress: Option<&str>,
+        diagnostics: SeverityCounts,
     ) -> StatusBarLayout {
         Self::render_status(
             frame,
@@ -281,7 +305,15 @@ impl StatusBarRenderer {
             update_available,
             warning_level,
             general_warning_count,
+            blocked_command_count,
             hover,
+            data_path,
+            input_locked,
+            read_only,
+            protected,
+            search_match,
+            progress,
+            diagnostics,
         )
     }
 
@@ -297,34 +329,59 @@ impl StatusBarRenderer {
         // Create spans for the prompt
         let mut spans = vec![Span::styled(prompt.message.clone(), base_style)];
 
-        // If there's a selection, split the input into parts
-        if let Some((sel_start, sel_end)) = prompt.selection_range() {
-            let input = &prompt.input;
+        // Input too long for the field scrolls horizontally to keep the
+        // cursor visible, with "<" and ">" marking the hidden ends
+        // Use display width (not byte length) for proper handling of:
+        // - Double-width CJK characters
+        // - Zero-width combining characters (Thai diacritics, etc.)
+        let message_width = str_width(&prompt.message);
+        let field_width = (area.width as usize).saturating_sub(message_width);
+        let input = &prompt.input;
+        let window = truncate::scroll_window(input, prompt.cursor_pos, field_width);
+        let indicator_style = Style::default()
+            .fg(theme.menu_highlight_fg)
+            .bg(theme.prompt_bg);
+        if window.clipped_left {
+            spans.push(Span::styled("<", indicator_style));
+        }
 
-            // Text before selection
-            if sel_start > 0 {
-                spans.push(Span::styled(input[..sel_start].to_string(), base_style));
-            }
+        // If there's a selection, split the visible input into parts
+        let (sel_start, sel_end) = prompt
+            .selection_range()
+            .map(|(start, end)| {
+                (
+                    start.clamp(window.start, window.end),
+                    end.clamp(window.start, window.end),
+                )
+            })
+            .unwrap_or((window.end, window.end));
+
+        // Text before selection
+        if window.start < sel_start {
+            spans.push(Span::styled(
+                input[window.start..sel_start].to_string(),
+                base_style,
+            ));
+        }
 
-            // Selected text (blue background for visibility, cursor remains visible)
-            if sel_start < sel_end {
-                // Use theme colors for selection to ensure consistency across themes
-                let selection_style = Style::default()
-                    .fg(theme.prompt_selection_fg)
-                    .bg(theme.prompt_selection_bg);
-                spans.push(Span::styled(
-                    input[sel_start..sel_end].to_string(),
-                    selection_style,
-                ));
-            }
+        // Selected text (blue background for visibility, cursor remains visible)
+        if sel_start < sel_end {
+            // Use theme colors for selection to ensure consistency across themes
+            let selection_style = Style::default()
+                .fg(theme.prompt_selection_fg)
+                .bg(theme.prompt_selection_bg);
+            spans.push(Span::styled(
+                input[sel_start..sel_end].to_string(),
+                selection_style,
+            ));
+        }
 
-            // Text after selection
-            if sel_end < input.len() {
-                spans.push(Span::styled(input[sel_end..].to_string(), base_style));
-            }
-        } else {
-            // No selection, render entire input normally
-            spans.push(Span::styled(prompt.input.clone(), base_style));
+        // Text after selection
+        if sel_end < window.end {
+            spans.push(Span::styled(
+                input[sel_end..window.end].to_string(),
+                base_style,
+            ));
         }
 
         let line = Line::from(spans);
@@ -332,13 +389,16 @@ impl StatusBarRenderer {
 
         frame.render_widget(prompt_line, area);
 
+        if window.clipped_right && field_width > 0 {
+            let indicator_area = Rect::new(area.x + area.width.saturating_sub(1), area.y, 1, 1);
+            frame.render_widget(
+                Paragraph::new(Span::styled(">", indicator_style)),
+                indicator_area,
+            );
+        }
+
         // Set cursor position in the prompt
-        // Use display width (not byte length) for proper handling of:
-        // - Double-width CJK characters
-        // - Zero-width combining characters (Thai diacritics, etc.)
-        let message_width = str_width(&prompt.message);
-        let input_width_before_cursor = str_width(&prompt.input[..prompt.cursor_pos]);
-        let cursor_x = (message_width + input_width_before_cursor) as u16;
+        let cursor_x = (message_width + window.cursor_column(input, prompt.cursor_pos)) as u16;
         if cursor_x < area.width {
             frame.set_cursor_position((area.x + cursor_x, area.y));
         }
@@ -365,8 +425,12 @@ impl StatusBarRenderer {
 
         let mut spans = Vec::new();
 
-        // "Open: " prefix
-        let open_prompt = t!("file.open_prompt").to_string();
+        // "Open: " prefix, or the prompt's own for Insert File
+        let open_prompt = if prompt.prompt_type == PromptType::InsertFile {
+            prompt.message.clone()
+        } else {
+            t!("file.open_prompt").to_string()
+        };
         spans.push(Span::styled(open_prompt.clone(), base_style));
 
         // Calculate if we need to truncate
@@ -462,7 +526,15 @@ impl StatusBarRenderer {
         update_available: Option<&str>,
         warning_level: WarningLevel,
         general_warning_count: usize,
+        blocked_command_count: usize,
         hover: StatusBarHover,
+        data_path: Option<&str>,
+        input_locked: bool,
+        read_only: bool,
+        protected: bool,
+        search_match: Option<(usize, usize)>,
+        progress: Option<&str>,
+        diagnostics: SeverityCounts,
     ) -> StatusBarLayout {
         // Initialize layout tracking
         let mut layout = StatusBarLayout::default();
@@ -474,6 +546,17 @@ impl StatusBarRenderer {
         } else {
             ""
         };
+        let read_only = match (read_only, protected) {
+            (true, true) => " [RO] [protected]",
+            (true, false) => " [RO]",
+            (false, true) => " [protected]",
+            (false, false) => "",
+        };
+        let lock = if input_locked {
+            format!("{} ", glyphs().locked)
+        } else {
+            String::new()
+        };
 
         // Format chord state if present
         let chord_display = if !chord_state.is_empty() {
@@ -509,25 +592,10 @@ impl StatusBarRenderer {
             (line_num, col)
         };
 
-        // Count diagnostics by severity
-        let diagnostics = state.overlays.all();
-        let mut error_count = 0;
-        let mut warning_count = 0;
-        let mut info_count = 0;
-
-        // Use the lsp-diagnostic namespace to identify diagnostic overlays
-        let diagnostic_ns = crate::services::lsp::diagnostics::lsp_diagnostic_namespace();
-        for overlay in diagnostics {
-            if overlay.namespace.as_ref() == Some(&diagnostic_ns) {
-                // Check priority to determine severity
-                // Based on lsp_diagnostics.rs: Error=100, Warning=50, Info=30, Hint=10
-                match overlay.priority {
-                    100 => error_count += 1,
-                    50 => warning_count += 1,
-                    _ => info_count += 1,
-                }
-            }
-        }
+        // Diagnostic counts, from the same store as the workspace summary
+        let error_count = diagnostics.errors;
+        let warning_count = diagnostics.warnings;
+        let info_count = diagnostics.infos + diagnostics.hints;
 
         // Build diagnostics summary if there are any
         let diagnostics_summary = if error_count + warning_count + info_count > 0 {
@@ -553,6 +621,37 @@ impl StatusBarRenderer {
             String::new()
         };
 
+        // Size of the selection, only while something is selected
+        let selection_indicator = match state.selection_summary.get(&state.buffer, &state.cursors) {
+            Some(SelectionSummary {
+                lines: Some(lines),
+                chars: Some(chars),
+                ..
+            }) => format!(
+                " | {}",
+                t!("status.selection", lines = lines, chars = chars)
+            ),
+            Some(summary) => format!(" | {}", t!("status.selection_bytes", bytes = summary.bytes)),
+            None => String::new(),
+        };
+
+        // Build search match indicator for the focused split
+        let search_indicator = match search_match {
+            Some((current, total)) => {
+                format!(
+                    " | {}",
+                    t!("status.search_match", current = current, total = total)
+                )
+            }
+            None => String::new(),
+        };
+
+        // Invalid UTF-8 kept as placeholders, written back on save
+        let invalid_bytes_indicator = match state.buffer.invalid_byte_sequences() {
+            0 => String::new(),
+            count => format!(" | {}", t!("status.invalid_sequences", count = count)),
+        };
+
         // Build status message parts
         let mut message_parts: Vec<&str> = Vec::new();
         if let Some(msg) = status_message {
@@ -574,8 +673,12 @@ impl StatusBarRenderer {
 
         // Build left status (file info, position, diagnostics, messages)
         // Line and column are 0-indexed internally, but displayed as 1-indexed (standard editor convention)
+        let data_path = match data_path {
+            Some(path) if !path.is_empty() => format!(" | {path}"),
+            _ => String::new(),
+        };
         let base_status = format!(
-            "{filename}{modified} | Ln {}, Col {}{diagnostics_summary}{cursor_count_indicator}",
+            "{lock}{filename}{read_only}{modified} | Ln {}, Col {}{data_path}{diagnostics_summary}{cursor_count_indicator}{selection_indicator}{search_indicator}{invalid_bytes_indicator}",
             line + 1,
             col + 1
         );
@@ -583,7 +686,13 @@ impl StatusBarRenderer {
         let left_status = format!("{base_status}{chord_display}{message_suffix}");
 
         // Build right-side indicators (these stay fixed on the right)
-        // Order: [Line ending] [LSP indicator] [warning badge] [update] [Palette]
+        // Order: [Progress] [Line ending] [LSP indicator] [warning badge] [blocked commands] [update] [Palette]
+
+        // Progress of a long operation (click to cancel it)
+        let progress_indicator = progress
+            .map(|label| format!(" {} ", label))
+            .unwrap_or_default();
+        let progress_width = str_width(&progress_indicator);
 
         // Line ending indicator (clickable to change format)
         let line_ending_text = format!(" {} ", state.buffer.line_ending().display_name());
@@ -599,12 +708,23 @@ impl StatusBarRenderer {
 
         // General warning badge (right-aligned)
         let warning_badge = if general_warning_count > 0 {
-            format!(" [⚠ {}] ", general_warning_count)
+            format!(" [{} {}] ", glyphs().warning, general_warning_count)
         } else {
             String::new()
         };
         let warning_badge_width = str_width(&warning_badge);
 
+        // Blocked project commands badge (right-aligned)
+        let blocked_badge = if blocked_command_count > 0 {
+            format!(
+                " {} ",
+                t!("status.blocked_commands", count = blocked_command_count)
+            )
+        } else {
+            String::new()
+        };
+        let blocked_badge_width = str_width(&blocked_badge);
+
         // Build update indicator for right side (if update available)
         let update_indicator = update_available
             .map(|version| format!(" {} ", t!("status.update_available", version = version)));
@@ -622,12 +742,14 @@ impl StatusBarRenderer {
         let padded_cmd_palette = format!(" {} ", cmd_palette_indicator);
 
         // Calculate available width and right side width
-        // Right side: [Line ending] [LSP indicator] [warning badge] [update] [Palette]
+        // Right side: [Progress] [Line ending] [LSP indicator] [warning badge] [blocked commands] [update] [Palette]
         let available_width = area.width as usize;
         let cmd_palette_width = str_width(&padded_cmd_palette);
-        let right_side_width = line_ending_width
+        let right_side_width = progress_width
+            + line_ending_width
             + lsp_indicator_width
             + warning_badge_width
+            + blocked_badge_width
             + update_width
             + cmd_palette_width;
 
@@ -642,32 +764,10 @@ impl StatusBarRenderer {
 
             let mut spans = vec![];
 
-            // Truncate left status if it's too long (use visual width, not char count)
-            let left_visual_width = str_width(&left_status);
-            let displayed_left = if left_visual_width > left_max_width {
-                let truncate_at = left_max_width.saturating_sub(3); // -3 for "..."
-                if truncate_at > 0 {
-                    // Take characters up to visual width limit
-                    let mut width = 0;
-                    let truncated: String = left_status
-                        .chars()
-                        .take_while(|ch| {
-                            let w = char_width(*ch);
-                            if width + w <= truncate_at {
-                                width += w;
-                                true
-                            } else {
-                                false
-                            }
-                        })
-                        .collect();
-                    format!("{}...", truncated)
-                } else {
-                    String::from("...")
-                }
-            } else {
-                left_status.clone()
-            };
+            // Shorten a status too long for the bar, paths in it first, so
+            // the end of the message (often the reason) stays visible
+            let displayed_left =
+                truncate::fit_message(&left_status, left_max_width, glyphs().ellipsis);
 
             let displayed_left_len = str_width(&displayed_left);
 
@@ -703,6 +803,19 @@ impl StatusBarRenderer {
                 current_col = area.x + (available_width - right_side_width) as u16;
             }
 
+            // Add progress indicator if a long operation is running
+            if !progress_indicator.is_empty() {
+                layout.progress_indicator =
+                    Some((area.y, current_col, current_col + progress_width as u16));
+                current_col += progress_width as u16;
+                spans.push(Span::styled(
+                    progress_indicator.clone(),
+                    Style::default()
+                        .fg(theme.menu_highlight_fg)
+                        .bg(theme.menu_dropdown_bg),
+                ));
+            }
+
             // Add line ending indicator (clickable to change format)
             {
                 let is_hovering = hover == StatusBarHover::LineEndingIndicator;
@@ -785,6 +898,22 @@ impl StatusBarRenderer {
                 }
                 spans.push(Span::styled(warning_badge.clone(), style));
             }
+
+            // Add blocked project commands badge (click to review them)
+            if !blocked_badge.is_empty() {
+                layout.blocked_commands_badge = Some((
+                    area.y,
+                    current_col,
+                    current_col + blocked_badge_width as u16,
+                ));
+                current_col += blocked_badge_width as u16;
+                spans.push(Span::styled(
+                    blocked_badge.clone(),
+                    Style::default()
+                        .fg(theme.status_error_indicator_fg)
+                        .bg(theme.status_error_indicator_bg),
+                ));
+            }
             // Keep current_col in scope to avoid unused warning
             let _ = current_col;
 
@@ -810,44 +939,8 @@ impl StatusBarRenderer {
         } else {
             // Terminal too narrow or no command palette indicator - fill entire width with left status
             let mut spans = vec![];
-            let left_visual_width = str_width(&left_status);
-            let displayed_left = if left_visual_width > available_width {
-                let truncate_at = available_width.saturating_sub(3);
-                if truncate_at > 0 {
-                    // Take characters up to visual width limit
-                    let mut width = 0;
-                    let truncated: String = left_status
-                        .chars()
-                        .take_while(|ch| {
-                            let w = char_width(*ch);
-                            if width + w <= truncate_at {
-                                width += w;
-                                true
-                            } else {
-                                false
-                            }
-                        })
-                        .collect();
-                    format!("{}...", truncated)
-                } else {
-                    // Take characters up to available width
-                    let mut width = 0;
-                    left_status
-                        .chars()
-                        .take_while(|ch| {
-                            let w = char_width(*ch);
-                            if width + w <= available_width {
-                                width += w;
-                                true
-                            } else {
-                                false
-                            }
-                        })
-                        .collect()
-                }
-            } else {
-                left_status.clone()
-            };
+            let displayed_left =
+                truncate::fit_message(&left_status, available_width, glyphs().ellipsis);
 
             spans.push(Span::styled(
                 displayed_left.clone(),
@@ -857,9 +950,10 @@ impl StatusBarRenderer {
             ));
 
             // Fill remaining width
-            if displayed_left.len() < available_width {
+            let displayed_left_len = str_width(&displayed_left);
+            if displayed_left_len < available_width {
                 spans.push(Span::styled(
-                    " ".repeat(available_width - displayed_left.len()),
+                    " ".repeat(available_width - displayed_left_len),
                     Style::default()
                         .fg(theme.status_bar_fg)
                         .bg(theme.status_bar_bg),
@@ -879,7 +973,8 @@ impl StatusBarRenderer {
     /// Render the search options bar (shown when search prompt is active)
     ///
     /// Displays checkboxes for search options with their keyboard shortcuts:
-    /// - Case Sensitive (Alt+C)
+    /// - Case Sensitive (Alt+C), or with `smart_case` the case matching of the
+    ///   query: `[Aa]` matching case, `[aa]` ignoring it
     /// - Whole Word (Alt+W)
     /// - Regex (Alt+R)
     /// - Confirm Each (Alt+I) - only shown in replace mode
@@ -890,6 +985,7 @@ impl StatusBarRenderer {
         frame: &mut Frame,
         area: Rect,
         case_sensitive: bool,
+        smart_case: bool,
         whole_word: bool,
         use_regex: bool,
         confirm_each: Option<bool>, // None = don't show, Some(value) = show with this state
@@ -933,7 +1029,12 @@ impl StatusBarRenderer {
         let regex_shortcut = get_shortcut(&crate::input::keybindings::Action::ToggleSearchRegex);
 
         // Build the options display with checkboxes
-        let case_checkbox = if case_sensitive { "[x]" } else { "[ ]" };
+        let case_checkbox = match (smart_case, case_sensitive) {
+            (true, true) => "[Aa]",
+            (true, false) => "[aa]",
+            (false, true) => "[x]",
+            (false, false) => "[ ]",
+        };
         let word_checkbox = if whole_word { "[x]" } else { "[ ]" };
         let regex_checkbox = if use_regex { "[x]" } else { "[ ]" };
 
@@ -973,7 +1074,11 @@ impl StatusBarRenderer {
         // Case Sensitive option
         let case_hovered = hover == SearchOptionsHover::CaseSensitive;
         let case_start = current_col;
-        let case_label = format!("{} {}", case_checkbox, t!("search.case_sensitive"));
+        let case_label = if smart_case {
+            format!("{} {}", case_checkbox, t!("search.smart_case"))
+        } else {
+            format!("{} {}", case_checkbox, t!("search.case_sensitive"))
+        };
         let case_shortcut_text = case_shortcut
             .as_ref()
             .map(|s| format!(" ({})", s))
@@ -2,6 +2,8 @@
 
 use crate::input::commands::CommandSource;
 use crate::primitives::display_width::{char_width, str_width};
+use crate::primitives::truncate;
+use crate::view::glyphs::glyphs;
 use crate::view::prompt::Prompt;
 use ratatui::layout::Rect;
 use ratatui::style::{Color, Modifier, Style};
@@ -52,6 +54,7 @@ impl SuggestionsRenderer {
         // Create a block with a border and background
         let block = Block::default()
             .borders(Borders::ALL)
+            .border_set(glyphs().border_set())
             .border_style(Style::default().fg(theme.popup_border_fg))
             .style(Style::default().bg(theme.suggestion_bg));
 
@@ -139,22 +142,7 @@ impl SuggestionsRenderer {
             let name = &suggestion.text;
             let name_visual_width = str_width(name);
             let name_text = if name_visual_width > name_column_width {
-                // Truncate name by visual width
-                let truncate_at = name_column_width.saturating_sub(1); // -1 for "…"
-                let mut width = 0;
-                let truncated: String = name
-                    .chars()
-                    .take_while(|ch| {
-                        let w = char_width(*ch);
-                        if width + w <= truncate_at {
-                            width += w;
-                            true
-                        } else {
-                            false
-                        }
-                    })
-                    .collect();
-                format!("{}…", truncated)
+                glyphs().truncate(name, name_column_width)
             } else {
                 name.clone()
             };
@@ -239,28 +227,9 @@ impl SuggestionsRenderer {
                     let desc_width = available_width
                         .saturating_sub(fixed_columns_width)
                         .saturating_sub(source_reserved);
-                    // Use visual width for truncation to handle double-width characters
-                    let desc_visual_width = str_width(desc);
-                    let desc_text = if desc_visual_width > desc_width {
-                        // Truncate description by visual width
-                        let truncate_at = desc_width.saturating_sub(3); // -3 for "..."
-                        let mut width = 0;
-                        let truncated: String = desc
-                            .chars()
-                            .take_while(|ch| {
-                                let w = char_width(*ch);
-                                if width + w <= truncate_at {
-                                    width += w;
-                                    true
-                                } else {
-                                    false
-                                }
-                            })
-                            .collect();
-                        format!("{}...", truncated)
-                    } else {
-                        desc.clone()
-                    };
+                    // Descriptions are often paths: shorten their middle so
+                    // the file name stays visible
+                    let desc_text = truncate::fit_message(desc, desc_width, glyphs().ellipsis);
                     let desc_display_width = str_width(&desc_text);
                     spans.push(Span::styled(desc_text, base_style));
                     // Pad description to fill its allocated space
@@ -309,22 +278,7 @@ impl SuggestionsRenderer {
                 };
                 let source_visual_width = str_width(&source_text);
                 let source_display = if source_visual_width > source_column_width {
-                    // Truncate source by visual width
-                    let truncate_at = source_column_width.saturating_sub(1); // -1 for "…"
-                    let mut width = 0;
-                    let truncated: String = source_text
-                        .chars()
-                        .take_while(|ch| {
-                            let w = char_width(*ch);
-                            if width + w <= truncate_at {
-                                width += w;
-                                true
-                            } else {
-                                false
-                            }
-                        })
-                        .collect();
-                    format!("{}…", truncated)
+                    glyphs().truncate(&source_text, source_column_width)
                 } else {
                     source_text
                 };
@@ -2,8 +2,10 @@
 
 use crate::app::BufferMetadata;
 use crate::model::event::BufferId;
+use crate::primitives::display_text::{sanitize_line, MAX_LABEL_CHARS};
 use crate::primitives::display_width::str_width;
 use crate::state::EditorState;
+use crate::view::glyphs::glyphs;
 use ratatui::layout::Rect;
 use ratatui::style::{Modifier, Style};
 use ratatui::text::{Line, Span};
@@ -148,7 +150,13 @@ impl TabsRenderer {
                     .or_else(|| meta.map(|m| m.display_name.as_str()))
             }
             .unwrap_or("[No Name]");
+            let name = sanitize_line(name, MAX_LABEL_CHARS, glyphs().ellipsis);
 
+            let pin = if meta.is_some_and(|m| m.pinned) {
+                format!("{} ", glyphs().pinned)
+            } else {
+                String::new()
+            };
             let modified = if state.buffer.is_modified() { "*" } else { "" };
             let binary_indicator = if buffer_metadata.get(id).map(|m| m.binary).unwrap_or(false) {
                 " [BIN]"
@@ -196,13 +204,13 @@ impl TabsRenderer {
                 base_style
             };
 
-            // Build tab content: " {name}{modified}{binary_indicator} "
-            let tab_name_text = format!(" {name}{modified}{binary_indicator} ");
+            // Build tab content: " {pin}{name}{modified}{binary_indicator} "
+            let tab_name_text = format!(" {pin}{name}{modified}{binary_indicator} ");
             let tab_name_width = str_width(&tab_name_text);
 
             // Close button: "× "
-            let close_text = "× ";
-            let close_width = str_width(close_text);
+            let close_text = format!("{} ", glyphs().close);
+            let close_width = str_width(&close_text);
 
             let total_width = tab_name_width + close_width;
 
@@ -214,10 +222,7 @@ impl TabsRenderer {
             // Add name span
             all_tab_spans.push((Span::styled(tab_name_text, base_style), tab_name_width));
             // Add close button span (can have different style when hovered)
-            all_tab_spans.push((
-                Span::styled(close_text.to_string(), close_style),
-                close_width,
-            ));
+            all_tab_spans.push((Span::styled(close_text, close_style), close_width));
 
             // Add a small separator between tabs if it's not the last tab
             if idx < split_buffers.len() - 1 {
@@ -0,0 +1,53 @@
+//! Placeholder drawn instead of the editor when the terminal is too small
+//!
+//! Below [`MIN_WIDTH`] x [`MIN_HEIGHT`] there isn't room for the menu bar,
+//! a tab bar, a line of text, the status bar and the prompt line, so the
+//! whole screen says how much room is needed instead.
+
+use ratatui::layout::{Alignment, Rect};
+use ratatui::style::Style;
+use ratatui::widgets::{Block, Clear, Paragraph, Wrap};
+use ratatui::Frame;
+use rust_i18n::t;
+
+use crate::primitives::display_width::str_width;
+use crate::view::theme::Theme;
+
+/// Narrowest terminal the editor is drawn in
+pub const MIN_WIDTH: u16 = 20;
+
+/// Shortest terminal the editor is drawn in
+pub const MIN_HEIGHT: u16 = 5;
+
+/// Whether `area` is too small to draw the editor in
+pub fn is_too_small(area: Rect) -> bool {
+    area.width < MIN_WIDTH || area.height < MIN_HEIGHT
+}
+
+/// Draw the message, wrapped and centered in as much of it as fits
+pub fn render(frame: &mut Frame, area: Rect, theme: &Theme) {
+    if area.width == 0 || area.height == 0 {
+        return;
+    }
+    frame.render_widget(Clear, area);
+
+    let message = t!(
+        "view.terminal_too_small",
+        width = MIN_WIDTH,
+        height = MIN_HEIGHT
+    )
+    .to_string();
+    let rows = (str_width(&message) as u16)
+        .div_ceil(area.width)
+        .min(area.height);
+    let top = (area.height - rows) / 2;
+    let style = Style::default().fg(theme.editor_fg).bg(theme.editor_bg);
+    frame.render_widget(Block::default().style(style), area);
+    frame.render_widget(
+        Paragraph::new(message)
+            .style(style)
+            .alignment(Alignment::Center)
+            .wrap(Wrap { trim: true }),
+        Rect::new(area.x, area.y + top, area.width, area.height - top),
+    );
+}
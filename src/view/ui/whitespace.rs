@@ -0,0 +1,288 @@
+//! Visible whitespace and control character placeholders
+//!
+//! [`char_marks`] classifies the characters of a display line before it is
+//! drawn: which are whitespace (and whether they trail the line), and which
+//! belong to a `<XX>` control character placeholder. The renderer then draws
+//! the theme's symbols cell for cell in their place, so widths, cursor
+//! movement and mouse mapping don't change with the setting.
+
+use crate::config::WhitespaceRendering;
+use crate::primitives::display_width::{char_width, str_width};
+use crate::view::glyphs::glyphs;
+use crate::view::theme::WhitespaceTheme;
+use crate::view::ui::view_pipeline::{LineStart, ViewLine};
+
+/// What a display character stands for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MarkKind {
+    Space,
+    /// First cell of a tab
+    TabStart,
+    /// Rest of a tab
+    TabFill,
+    /// Non-breaking or other unusual Unicode space
+    UnusualSpace,
+    /// Part of a `<XX>` placeholder
+    Control,
+}
+
+/// Classification of one display character
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CharMark {
+    pub kind: MarkKind,
+    /// Whitespace after the last visible character of the line
+    pub trailing: bool,
+    /// Drawn as a symbol under the current setting (selected whitespace is
+    /// drawn regardless)
+    pub shown: bool,
+}
+
+impl CharMark {
+    pub fn is_whitespace(&self) -> bool {
+        self.kind != MarkKind::Control
+    }
+}
+
+/// Spaces that look like a plain space but aren't one
+pub fn is_unusual_space(ch: char) -> bool {
+    matches!(
+        ch,
+        '\u{00A0}' | '\u{1680}' | '\u{2000}'..='\u{200A}' | '\u{202F}' | '\u{205F}' | '\u{3000}'
+    )
+}
+
+/// Classify the characters of `line`, indexed like its text
+///
+/// Characters that aren't whitespace or placeholders get `None`, as do
+/// injected characters without a source byte.
+pub fn char_marks(line: &ViewLine, mode: WhitespaceRendering) -> Vec<Option<CharMark>> {
+    let chars: Vec<char> = line.text.chars().collect();
+    let source = &line.char_source_bytes;
+    let source_at = |i: usize| source.get(i).copied().flatten();
+
+    let mut kinds: Vec<Option<MarkKind>> = vec![None; chars.len()];
+    let mut i = 0;
+    while i < chars.len() {
+        let Some(byte) = source_at(i) else {
+            i += 1;
+            continue;
+        };
+        // Characters expanded from one source byte: a tab or a placeholder
+        let mut end = i + 1;
+        while end < chars.len() && source_at(end) == Some(byte) {
+            end += 1;
+        }
+        if line.tab_starts.contains(&i) {
+            kinds[i] = Some(MarkKind::TabStart);
+            for kind in &mut kinds[i + 1..end] {
+                *kind = Some(MarkKind::TabFill);
+            }
+        } else if end - i > 1 {
+            for kind in &mut kinds[i..end] {
+                *kind = Some(MarkKind::Control);
+            }
+        } else if chars[i] == ' ' {
+            kinds[i] = Some(MarkKind::Space);
+        } else if is_unusual_space(chars[i]) && char_width(chars[i]) > 0 {
+            kinds[i] = Some(MarkKind::UnusualSpace);
+        }
+        i = end;
+    }
+
+    let is_space = |kind: Option<MarkKind>| kind.is_some_and(|k| k != MarkKind::Control);
+    let content_end = if line.ends_with_newline {
+        chars.len() - 1
+    } else {
+        chars.len()
+    };
+    // A line cut by wrapping doesn't end there, and neither does a
+    // continuation start a line
+    let ends_line = !line.ends_with_newline || source_at(content_end).is_some();
+    let starts_line = line.line_start != LineStart::AfterBreak;
+    let first_text = (0..content_end).find(|&i| !is_space(kinds[i]));
+    let last_text = (0..content_end).rev().find(|&i| !is_space(kinds[i]));
+
+    (0..chars.len())
+        .map(|i| {
+            let kind = kinds[i]?;
+            let trailing = ends_line && i < content_end && last_text.is_none_or(|last| i > last);
+            let leading = starts_line && first_text.is_none_or(|first| i < first);
+            let in_run =
+                (i > 0 && is_space(kinds[i - 1])) || is_space(kinds.get(i + 1).copied().flatten());
+            let shown = match (mode, kind) {
+                (_, MarkKind::Control) => true,
+                (WhitespaceRendering::None, _) => false,
+                (_, MarkKind::UnusualSpace) | (WhitespaceRendering::All, _) => true,
+                (WhitespaceRendering::Trailing, _) => trailing,
+                (WhitespaceRendering::Boundary, MarkKind::Space) => leading || trailing || in_run,
+                (WhitespaceRendering::Boundary, _) => true,
+            };
+            Some(CharMark {
+                kind,
+                trailing,
+                shown,
+            })
+        })
+        .collect()
+}
+
+/// Text drawn for a marked whitespace character, as wide as `ch`
+pub fn symbol_for(mark: &CharMark, ch: char, theme: &WhitespaceTheme) -> Option<String> {
+    let glyphs = glyphs();
+    let (symbol, fallback) = match mark.kind {
+        MarkKind::Space => (theme.space.symbol, glyphs.space_mark),
+        MarkKind::TabStart => (theme.tab.symbol, glyphs.tab),
+        MarkKind::UnusualSpace => (theme.unusual_space.symbol, glyphs.unusual_space_mark),
+        MarkKind::TabFill | MarkKind::Control => return None,
+    };
+    let symbol = if glyphs.ascii_only && !symbol.is_ascii() {
+        fallback.to_string()
+    } else {
+        symbol.to_string()
+    };
+    let padding = char_width(ch).saturating_sub(str_width(&symbol));
+    Some(format!("{}{}", symbol, " ".repeat(padding)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::plugins::api::{ViewTokenWire, ViewTokenWireKind};
+    use crate::view::ui::view_pipeline::ViewLineIterator;
+
+    fn text(offset: usize, s: &str) -> ViewTokenWire {
+        ViewTokenWire {
+            source_offset: Some(offset),
+            kind: ViewTokenWireKind::Text(s.to_string()),
+            style: None,
+        }
+    }
+
+    fn space(offset: usize) -> ViewTokenWire {
+        ViewTokenWire {
+            source_offset: Some(offset),
+            kind: ViewTokenWireKind::Space,
+            style: None,
+        }
+    }
+
+    fn newline(offset: usize) -> ViewTokenWire {
+        ViewTokenWire {
+            source_offset: Some(offset),
+            kind: ViewTokenWireKind::Newline,
+            style: None,
+        }
+    }
+
+    /// One letter per character: s(pace), t(ab start), f(ill), u(nusual),
+    /// c(ontrol), upper case when shown, "." otherwise
+    fn describe(tokens: &[ViewTokenWire], mode: WhitespaceRendering) -> String {
+        let line = ViewLineIterator::new(tokens, false, false, 4)
+            .next()
+            .unwrap();
+        char_marks(&line, mode)
+            .iter()
+            .map(|mark| match mark {
+                None => '.',
+                Some(mark) => {
+                    let c = match mark.kind {
+                        MarkKind::Space => 's',
+                        MarkKind::TabStart => 't',
+                        MarkKind::TabFill => 'f',
+                        MarkKind::UnusualSpace => 'u',
+                        MarkKind::Control => 'c',
+                    };
+                    if mark.shown {
+                        c.to_ascii_uppercase()
+                    } else {
+                        c
+                    }
+                }
+            })
+            .collect()
+    }
+
+    fn sample() -> Vec<ViewTokenWire> {
+        // " a b  c \t\u{a0} \n", the tab filling columns 8 to 11
+        vec![
+            space(0),
+            text(1, "a"),
+            space(2),
+            text(3, "b"),
+            space(4),
+            space(5),
+            text(6, "c"),
+            space(7),
+            text(8, "\t"),
+            text(9, "\u{a0}"),
+            space(11),
+            newline(12),
+        ]
+    }
+
+    #[test]
+    fn test_marks_per_mode() {
+        let tokens = sample();
+        assert_eq!(
+            describe(&tokens, WhitespaceRendering::None),
+            "s.s.ss.stfffus."
+        );
+        assert_eq!(
+            describe(&tokens, WhitespaceRendering::Trailing),
+            "s.s.ss.STFFFUS."
+        );
+        assert_eq!(
+            describe(&tokens, WhitespaceRendering::Boundary),
+            "S.s.SS.STFFFUS."
+        );
+        assert_eq!(
+            describe(&tokens, WhitespaceRendering::All),
+            "S.S.SS.STFFFUS."
+        );
+    }
+
+    #[test]
+    fn test_wrapped_segment_has_no_trailing_whitespace() {
+        let tokens = vec![
+            text(0, "a"),
+            space(1),
+            ViewTokenWire {
+                source_offset: None,
+                kind: ViewTokenWireKind::Break,
+                style: None,
+            },
+        ];
+        assert_eq!(describe(&tokens, WhitespaceRendering::Trailing), ".s.");
+    }
+
+    #[test]
+    fn test_control_placeholder() {
+        let tokens = vec![
+            text(0, "a"),
+            ViewTokenWire {
+                source_offset: Some(1),
+                kind: ViewTokenWireKind::BinaryByte(0x0C),
+                style: None,
+            },
+            newline(2),
+        ];
+        assert_eq!(describe(&tokens, WhitespaceRendering::None), ".CCCC.");
+    }
+
+    #[test]
+    fn test_symbol_keeps_width() {
+        let theme = WhitespaceTheme::new(
+            ratatui::style::Color::Gray,
+            ratatui::style::Color::Red,
+            ratatui::style::Color::Red,
+            ratatui::style::Color::Yellow,
+        );
+        let mark = CharMark {
+            kind: MarkKind::UnusualSpace,
+            trailing: false,
+            shown: true,
+        };
+        assert_eq!(symbol_for(&mark, '\u{a0}', &theme).unwrap(), "⍽");
+        assert_eq!(symbol_for(&mark, '\u{3000}', &theme).unwrap(), "⍽ ");
+    }
+}
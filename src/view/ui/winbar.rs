@@ -0,0 +1,258 @@
+//! Winbar: the line at the top of a split naming what it shows
+//!
+//! It holds the buffer's name with the unsaved-changes marker, the key path
+//! at the cursor of data files, and the segments plugins set on the split
+//! with `setWinbarSegment`, flush right. A line too narrow for all of it
+//! drops plugin segments, lowest priority first, then cuts the start of the
+//! key path, then the end of the name.
+
+use ratatui::layout::Rect;
+use ratatui::style::{Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::Paragraph;
+use ratatui::Frame;
+
+use crate::primitives::display_width::{char_width, str_width};
+use crate::view::glyphs::glyphs;
+use crate::view::theme::{DecorationColor, Theme};
+
+/// Text a plugin shows in a split's winbar
+#[derive(Debug, Clone, PartialEq)]
+pub struct WinbarSegment {
+    /// Setting a segment replaces the one with the same id
+    pub id: String,
+    pub text: String,
+    /// None for the winbar's own text color
+    pub color: Option<DecorationColor>,
+    /// Segments with a lower priority are dropped first when the line is
+    /// too narrow
+    pub priority: i32,
+}
+
+/// Set `segment` in `segments`, replacing the one with its id; empty text
+/// removes it
+pub fn set_segment(segments: &mut Vec<WinbarSegment>, segment: WinbarSegment) {
+    let existing = segments.iter().position(|s| s.id == segment.id);
+    match existing {
+        Some(index) if segment.text.is_empty() => {
+            segments.remove(index);
+        }
+        Some(index) => segments[index] = segment,
+        None if segment.text.is_empty() => {}
+        None => segments.push(segment),
+    }
+}
+
+/// What a split's winbar shows
+pub struct Winbar<'a> {
+    /// Name of the buffer in the split
+    pub name: &'a str,
+    /// The buffer has unsaved changes
+    pub modified: bool,
+    /// Key path at the cursor
+    pub breadcrumb: Option<&'a str>,
+    /// Segments plugins set on the split, in the order they were first set
+    pub segments: &'a [WinbarSegment],
+}
+
+/// The parts of a [`Winbar`] that fit a given width
+#[derive(Debug, PartialEq)]
+struct WinbarLayout {
+    name: String,
+    breadcrumb: Option<String>,
+    /// Indices of the segments shown, in order
+    segments: Vec<usize>,
+}
+
+impl Winbar<'_> {
+    /// Draw the winbar in the one-row `area`
+    pub fn render(&self, frame: &mut Frame, area: Rect, theme: &Theme, is_active: bool) {
+        let layout = self.layout(area.width as usize);
+        let fg = if is_active {
+            theme.editor_fg
+        } else {
+            theme.line_number_fg
+        };
+        let style = Style::default().fg(fg).bg(theme.editor_bg);
+        let dim = style.fg(theme.line_number_fg);
+
+        let mut spans = vec![
+            Span::styled(" ", style),
+            Span::styled(layout.name, style.add_modifier(Modifier::BOLD)),
+        ];
+        if self.modified {
+            spans.push(Span::styled(format!(" {}", glyphs().modified), style));
+        }
+        if let Some(breadcrumb) = layout.breadcrumb {
+            spans.push(Span::styled(format!(" {} ", glyphs().breadcrumb), dim));
+            spans.push(Span::styled(breadcrumb, style));
+        }
+
+        let used: usize = spans.iter().map(|span| span.width()).sum();
+        let right_width = self.segments_width(&layout.segments);
+        if right_width > 0 {
+            let padding = (area.width as usize).saturating_sub(used + right_width);
+            spans.push(Span::styled(" ".repeat(padding + 1), style));
+            for &index in &layout.segments {
+                let segment = &self.segments[index];
+                let segment_style = match &segment.color {
+                    Some(color) => style.fg(color.resolve(theme, fg)),
+                    None => style,
+                };
+                spans.push(Span::styled(segment.text.clone(), segment_style));
+                spans.push(Span::styled(" ", style));
+            }
+        }
+
+        frame.render_widget(Paragraph::new(Line::from(spans)).style(style), area);
+    }
+
+    /// Fit the winbar into `width` columns
+    fn layout(&self, width: usize) -> WinbarLayout {
+        let marker_width = if self.modified {
+            1 + str_width(glyphs().modified)
+        } else {
+            0
+        };
+        let fixed = 1 + str_width(self.name) + marker_width;
+        let separator_width = 2 + str_width(glyphs().breadcrumb);
+        let breadcrumb_width = self
+            .breadcrumb
+            .map_or(0, |breadcrumb| separator_width + str_width(breadcrumb));
+
+        let mut shown: Vec<usize> = (0..self.segments.len()).collect();
+        while !shown.is_empty() && fixed + breadcrumb_width + self.segments_width(&shown) > width {
+            // Of equal priorities, the segment set last goes first
+            let (position, _) = shown
+                .iter()
+                .enumerate()
+                .rev()
+                .min_by_key(|(_, &index)| self.segments[index].priority)
+                .expect("shown is not empty");
+            shown.remove(position);
+        }
+
+        let room = width.saturating_sub(fixed + separator_width + self.segments_width(&shown));
+        let breadcrumb = self.breadcrumb.and_then(|breadcrumb| {
+            if str_width(breadcrumb) <= room {
+                Some(breadcrumb.to_string())
+            } else if room > str_width(glyphs().ellipsis) {
+                Some(keep_end(breadcrumb, room))
+            } else {
+                None
+            }
+        });
+
+        let name = if fixed > width {
+            glyphs().truncate(self.name, width.saturating_sub(1 + marker_width))
+        } else {
+            self.name.to_string()
+        };
+
+        WinbarLayout {
+            name,
+            breadcrumb,
+            segments: shown,
+        }
+    }
+
+    /// Columns taken by the segments `shown`, with the space in front of
+    /// them and after each
+    fn segments_width(&self, shown: &[usize]) -> usize {
+        if shown.is_empty() {
+            return 0;
+        }
+        1 + shown
+            .iter()
+            .map(|&index| str_width(&self.segments[index].text) + 1)
+            .sum::<usize>()
+    }
+}
+
+/// The end of `text` that fits in `width` columns after an ellipsis
+fn keep_end(text: &str, width: usize) -> String {
+    let ellipsis = glyphs().ellipsis;
+    let mut used = str_width(ellipsis);
+    let mut start = text.len();
+    for (i, c) in text.char_indices().rev() {
+        used += char_width(c);
+        if used > width {
+            break;
+        }
+        start = i;
+    }
+    format!("{ellipsis}{}", &text[start..])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn segment(id: &str, text: &str, priority: i32) -> WinbarSegment {
+        WinbarSegment {
+            id: id.to_string(),
+            text: text.to_string(),
+            color: None,
+            priority,
+        }
+    }
+
+    #[test]
+    fn test_set_segment_replaces_and_removes_by_id() {
+        let mut segments = Vec::new();
+        set_segment(&mut segments, segment("a", "one", 0));
+        set_segment(&mut segments, segment("b", "two", 0));
+        set_segment(&mut segments, segment("a", "three", 5));
+        assert_eq!(
+            segments,
+            vec![segment("a", "three", 5), segment("b", "two", 0)]
+        );
+
+        set_segment(&mut segments, segment("a", "", 0));
+        set_segment(&mut segments, segment("c", "", 0));
+        assert_eq!(segments, vec![segment("b", "two", 0)]);
+    }
+
+    #[test]
+    fn test_layout_drops_lowest_priority_segments_first() {
+        let segments = [segment("a", "high", 10), segment("b", "low", 1)];
+        let winbar = Winbar {
+            name: "main.rs",
+            modified: false,
+            breadcrumb: None,
+            segments: &segments,
+        };
+        // " main.rs" is 8 columns, " high low " 10 more
+        assert_eq!(winbar.layout(18).segments, vec![0, 1]);
+        assert_eq!(winbar.layout(17).segments, vec![0]);
+        assert_eq!(winbar.layout(14).segments, vec![0]);
+        assert_eq!(winbar.layout(13).segments, Vec::<usize>::new());
+        assert_eq!(winbar.layout(13).name, "main.rs");
+    }
+
+    #[test]
+    fn test_layout_cuts_breadcrumb_from_the_start_then_the_name() {
+        let winbar = Winbar {
+            name: "config.yaml",
+            modified: false,
+            breadcrumb: Some("spec.containers[1].image"),
+            segments: &[],
+        };
+        let ellipsis = glyphs().ellipsis;
+        let separator_width = 2 + str_width(glyphs().breadcrumb);
+
+        let full = 12 + separator_width + 24;
+        assert_eq!(
+            winbar.layout(full).breadcrumb.as_deref(),
+            Some("spec.containers[1].image")
+        );
+        let cut = winbar.layout(full - 4).breadcrumb.unwrap();
+        assert!(cut.starts_with(ellipsis) && cut.ends_with("[1].image"));
+        assert_eq!(str_width(&cut), 20);
+
+        let narrow = winbar.layout(8);
+        assert_eq!(narrow.breadcrumb, None);
+        assert_eq!(str_width(&narrow.name), 7);
+        assert!(narrow.name.ends_with(ellipsis));
+    }
+}
@@ -3,6 +3,15 @@ use crate::model::cursor::Cursor;
 use crate::primitives::display_width::{char_width, str_width};
 use crate::primitives::line_wrapping::{char_position_to_segment, wrap_line, WrapConfig};
 use crate::view::ui::view_pipeline::ViewLine;
+
+/// Where [`Viewport::place_line`] puts a line
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LinePlacement {
+    Top,
+    Center,
+    Bottom,
+}
+
 /// The viewport - what portion of the buffer is visible
 #[derive(Debug, Clone)]
 pub struct Viewport {
@@ -164,6 +173,141 @@ impl Viewport {
         self.set_top_byte_with_limit(buffer, new_position);
     }
 
+    /// Wrapping used to count screen rows, None when lines don't wrap
+    fn row_wrap_config(&self, buffer: &Buffer) -> Option<WrapConfig> {
+        self.line_wrap_enabled
+            .then(|| WrapConfig::new(self.width as usize, self.gutter_width(buffer), true))
+    }
+
+    /// Screen rows a line takes (1 unless it wraps)
+    fn line_rows(wrap_config: Option<&WrapConfig>, content: &str) -> usize {
+        match wrap_config {
+            Some(config) => wrap_line(content.trim_end_matches('\n'), config)
+                .len()
+                .max(1),
+            None => 1,
+        }
+    }
+
+    /// Scroll so the screen row of `position` lands at the top, middle or
+    /// bottom of the view
+    ///
+    /// Counts wrapped rows when lines wrap. The view can only start at a line
+    /// start, so a wrapped line above the target that doesn't fit whole is
+    /// left out, and the view is clamped at the start and end of the buffer.
+    pub fn place_line(&mut self, buffer: &mut Buffer, position: usize, placement: LinePlacement) {
+        let height = self.visible_line_count().max(1);
+        let target_row = match placement {
+            LinePlacement::Top => 0,
+            LinePlacement::Center => height / 2,
+            LinePlacement::Bottom => height - 1,
+        };
+        let wrap_config = self.row_wrap_config(buffer);
+
+        // Rows of the position's own line above its segment
+        let (line_start, mut rows_above) = {
+            let mut iter = buffer.line_iterator(position, 80);
+            let line_start = iter.current_position();
+            let segment = match (&wrap_config, iter.next()) {
+                (Some(config), Some((_, content))) => {
+                    let segments = wrap_line(content.trim_end_matches('\n'), config);
+                    char_position_to_segment(position.saturating_sub(line_start), &segments).0
+                }
+                _ => 0,
+            };
+            (line_start, segment)
+        };
+
+        let mut top = line_start;
+        let mut iter = buffer.line_iterator(line_start, 80);
+        while rows_above < target_row {
+            let Some((start, content)) = iter.prev() else {
+                break;
+            };
+            rows_above += Self::line_rows(wrap_config.as_ref(), &content);
+            if rows_above > target_row {
+                break;
+            }
+            top = start;
+        }
+        drop(iter);
+
+        self.top_view_line_offset = 0;
+        self.set_top_byte_with_limit(buffer, top);
+    }
+
+    /// Starts of the first and last lines the cursor can be on without
+    /// [`Self::ensure_visible`] scrolling the view: `scroll_offset` rows in
+    /// from either edge, except at the start and end of the buffer
+    pub fn cursor_line_range(&self, buffer: &mut Buffer) -> (usize, usize) {
+        let height = self.visible_line_count().max(1);
+        let offset = self.scroll_offset.min(height / 2);
+        let wrap_config = self.row_wrap_config(buffer);
+        let last_line_start = buffer.line_iterator(buffer.len(), 80).current_position();
+
+        let mut range: Option<(usize, usize)> = None;
+        let mut rows_above = 0;
+        let mut iter = buffer.line_iterator(self.top_byte, 80);
+        while rows_above < height {
+            let start = iter.current_position();
+            let line = iter.next();
+            // The same bounds as ensure_visible for a cursor on this line
+            let below_top = if wrap_config.is_some() {
+                rows_above >= offset
+            } else {
+                rows_above > offset
+            };
+            let above_bottom = rows_above < height.saturating_sub(offset);
+            let is_last = line.is_none() || start >= last_line_start;
+            if (below_top || self.top_byte == 0) && (above_bottom || is_last) {
+                range = Some((range.map_or(start, |(first, _)| first), start));
+            }
+            match line {
+                Some((_, content)) if !is_last => {
+                    rows_above += Self::line_rows(wrap_config.as_ref(), &content);
+                }
+                _ => break,
+            }
+        }
+
+        range.unwrap_or((self.top_byte, self.top_byte))
+    }
+
+    /// Scroll horizontally by N columns (negative scrolls left)
+    ///
+    /// Clamped at the start of the line, and on the right so the end of the
+    /// longest visible line stays on screen. Scrolling right never moves the view
+    /// left, so repeated scrolls at the edge don't jitter.
+    /// Does nothing when line wrapping is enabled.
+    pub fn scroll_horizontal(&mut self, buffer: &mut Buffer, columns: isize) {
+        if self.line_wrap_enabled {
+            return;
+        }
+        if columns < 0 {
+            self.left_column = self.left_column.saturating_sub(columns.unsigned_abs());
+            return;
+        }
+
+        // Same visible width and limit as ensure_column_visible (gutter + scrollbar)
+        let visible_width = (self.width as usize)
+            .saturating_sub(self.gutter_width(buffer))
+            .saturating_sub(1);
+        let mut longest_line = 0;
+        let mut iter = buffer.line_iterator(self.top_byte, 80);
+        for _ in 0..self.visible_line_count() {
+            let Some((_, content)) = iter.next() else {
+                break;
+            };
+            longest_line = longest_line.max(content.trim_end_matches(['\n', '\r']).len());
+        }
+        let max_left_column = longest_line.saturating_sub(visible_width.saturating_sub(1));
+
+        self.left_column = self
+            .left_column
+            .saturating_add(columns as usize)
+            .min(max_left_column.max(self.left_column));
+    }
+
     /// Scroll through ViewLines (view-transform aware)
     ///
     /// This method scrolls through display lines rather than source lines,
@@ -20,9 +20,11 @@
 //! This ensures frame coherence: render always sees a consistent snapshot of virtual text.
 
 use ratatui::style::Style;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use crate::model::marker::{MarkerId, MarkerList};
+use crate::primitives::interner::Symbol;
+use crate::view::theme::{Theme, ThemedStyle};
 
 /// Position relative to the character at the marker position
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -58,18 +60,19 @@ impl VirtualTextPosition {
 
 /// Namespace for grouping virtual texts (for efficient bulk removal).
 /// Similar to OverlayNamespace - plugins create a namespace once and use it for all their virtual texts.
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
-pub struct VirtualTextNamespace(pub String);
+/// The name is interned, so namespaces are cheap to copy and compare.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct VirtualTextNamespace(Symbol);
 
 impl VirtualTextNamespace {
     /// Create a namespace from a string (for plugin registration)
     pub fn from_string(s: String) -> Self {
-        Self(s)
+        Self(Symbol::intern(&s))
     }
 
     /// Get the internal string representation
-    pub fn as_str(&self) -> &str {
-        &self.0
+    pub fn as_str(&self) -> &'static str {
+        self.0.as_str()
     }
 }
 
@@ -90,6 +93,17 @@ pub struct VirtualText {
     pub string_id: Option<String>,
     /// Optional namespace for bulk removal (like Overlay's namespace)
     pub namespace: Option<VirtualTextNamespace>,
+    /// Style with theme colors, used instead of `style` when set
+    pub themed_style: Option<ThemedStyle>,
+}
+
+impl VirtualText {
+    /// The style to draw with in `theme`
+    pub fn resolved_style(&self, theme: &Theme) -> Style {
+        self.themed_style
+            .as_ref()
+            .map_or(self.style, |style| style.resolve(theme))
+    }
 }
 
 /// Unique identifier for a virtual text entry
@@ -103,6 +117,8 @@ pub struct VirtualTextId(pub u64);
 pub struct VirtualTextManager {
     /// Map from virtual text ID to virtual text entry
     texts: HashMap<VirtualTextId, VirtualText>,
+    /// Entries of each namespace, so clearing one doesn't scan them all
+    namespaces: HashMap<VirtualTextNamespace, HashSet<VirtualTextId>>,
     /// Next ID to assign
     next_id: u64,
 }
@@ -112,6 +128,7 @@ impl VirtualTextManager {
     pub fn new() -> Self {
         Self {
             texts: HashMap::new(),
+            namespaces: HashMap::new(),
             next_id: 0,
         }
     }
@@ -154,6 +171,7 @@ impl VirtualTextManager {
                 priority,
                 string_id: None,
                 namespace: None,
+                themed_style: None,
             },
         );
 
@@ -188,6 +206,7 @@ impl VirtualTextManager {
                 priority,
                 string_id: Some(string_id),
                 namespace: None,
+                themed_style: None,
             },
         );
 
@@ -236,12 +255,22 @@ impl VirtualTextManager {
                 priority,
                 string_id: None,
                 namespace: Some(namespace),
+                themed_style: None,
             },
         );
+        self.namespaces.entry(namespace).or_default().insert(id);
 
         id
     }
 
+    /// Draw a virtual text entry with theme colors, so it follows theme
+    /// changes
+    pub fn set_themed_style(&mut self, id: VirtualTextId, style: ThemedStyle) {
+        if let Some(text) = self.texts.get_mut(&id) {
+            text.themed_style = Some(style);
+        }
+    }
+
     /// Remove a virtual text entry by its string identifier
     pub fn remove_by_id(&mut self, marker_list: &mut MarkerList, string_id: &str) -> bool {
         // Find the entry with matching string_id
@@ -259,24 +288,21 @@ impl VirtualTextManager {
 
         let mut removed = false;
         for id in to_remove {
-            if let Some(vtext) = self.texts.remove(&id) {
-                marker_list.delete(vtext.marker_id);
-                removed = true;
-            }
+            removed |= self.remove(marker_list, id);
         }
         removed
     }
 
     /// Remove all virtual text entries whose string_id starts with the given prefix
     pub fn remove_by_prefix(&mut self, marker_list: &mut MarkerList, prefix: &str) {
-        // Collect markers to delete
-        let markers_to_delete: Vec<(VirtualTextId, MarkerId)> = self
+        // Collect entries to remove
+        let to_remove: Vec<VirtualTextId> = self
             .texts
             .iter()
             .filter_map(|(id, vtext)| {
                 if let Some(ref sid) = vtext.string_id {
                     if sid.starts_with(prefix) {
-                        return Some((*id, vtext.marker_id));
+                        return Some(*id);
                     }
                 }
                 None
@@ -284,20 +310,26 @@ impl VirtualTextManager {
             .collect();
 
         // Delete markers and remove entries
-        for (id, marker_id) in markers_to_delete {
-            marker_list.delete(marker_id);
-            self.texts.remove(&id);
+        for id in to_remove {
+            self.remove(marker_list, id);
         }
     }
 
     /// Remove a virtual text entry
     pub fn remove(&mut self, marker_list: &mut MarkerList, id: VirtualTextId) -> bool {
-        if let Some(vtext) = self.texts.remove(&id) {
-            marker_list.delete(vtext.marker_id);
-            true
-        } else {
-            false
+        let Some(vtext) = self.texts.remove(&id) else {
+            return false;
+        };
+        marker_list.delete(vtext.marker_id);
+        if let Some(namespace) = vtext.namespace {
+            if let Some(ids) = self.namespaces.get_mut(&namespace) {
+                ids.remove(&id);
+                if ids.is_empty() {
+                    self.namespaces.remove(&namespace);
+                }
+            }
         }
+        true
     }
 
     /// Clear all virtual text entries
@@ -306,6 +338,7 @@ impl VirtualTextManager {
             marker_list.delete(vtext.marker_id);
         }
         self.texts.clear();
+        self.namespaces.clear();
     }
 
     /// Get the number of virtual text entries
@@ -318,6 +351,66 @@ impl VirtualTextManager {
         self.texts.is_empty()
     }
 
+    /// Estimated heap memory used by the entries, not counting their markers
+    pub fn memory_bytes(&self) -> usize {
+        use std::mem::size_of;
+        // Hash tables take a control byte per bucket besides the entry
+        let table = self.texts.capacity() * (size_of::<(VirtualTextId, VirtualText)>() + 1);
+        let strings: usize = self
+            .texts
+            .values()
+            .map(|vtext| {
+                vtext.text.capacity() + vtext.string_id.as_ref().map_or(0, String::capacity)
+            })
+            .sum();
+        let namespaces: usize = self
+            .namespaces
+            .values()
+            .map(|ids| {
+                size_of::<(VirtualTextNamespace, HashSet<VirtualTextId>)>()
+                    + ids.capacity() * (size_of::<VirtualTextId>() + 1)
+            })
+            .sum();
+        table + strings + namespaces
+    }
+
+    /// Give back the capacity kept from entries since removed
+    pub fn shrink_to_fit(&mut self) {
+        self.texts.shrink_to_fit();
+        self.namespaces.retain(|_, ids| !ids.is_empty());
+        self.namespaces.shrink_to_fit();
+        for ids in self.namespaces.values_mut() {
+            ids.shrink_to_fit();
+        }
+    }
+
+    /// Check that every entry's marker exists and lies within a buffer of
+    /// `buffer_len` bytes
+    pub fn check_invariants(
+        &self,
+        marker_list: &MarkerList,
+        buffer_len: usize,
+    ) -> Result<(), String> {
+        for (id, vtext) in &self.texts {
+            match marker_list.get_position(vtext.marker_id) {
+                None => {
+                    return Err(format!(
+                        "virtual text {} refers to deleted marker {:?}",
+                        id.0, vtext.marker_id
+                    ));
+                }
+                Some(pos) if pos > buffer_len => {
+                    return Err(format!(
+                        "virtual text {} at {} is past the end of the buffer ({} bytes)",
+                        id.0, pos, buffer_len
+                    ));
+                }
+                Some(_) => {}
+            }
+        }
+        Ok(())
+    }
+
     /// Query virtual texts in a byte range
     ///
     /// Returns a vector of (byte_position, &VirtualText) pairs, sorted by:
@@ -389,19 +482,7 @@ impl VirtualTextManager {
         marker_list: &mut MarkerList,
         namespace: &VirtualTextNamespace,
     ) {
-        let to_remove: Vec<VirtualTextId> = self
-            .texts
-            .iter()
-            .filter_map(|(id, vtext)| {
-                if vtext.namespace.as_ref() == Some(namespace) {
-                    Some(*id)
-                } else {
-                    None
-                }
-            })
-            .collect();
-
-        for id in to_remove {
+        for id in self.namespaces.remove(namespace).unwrap_or_default() {
             if let Some(vtext) = self.texts.remove(&id) {
                 marker_list.delete(vtext.marker_id);
             }
@@ -837,4 +918,67 @@ mod tests {
         assert_eq!(before.unwrap().text, "/*param=*/");
         assert_eq!(after.unwrap().text, ": Type");
     }
+
+    #[test]
+    fn test_check_invariants() {
+        let mut marker_list = MarkerList::new();
+        let mut manager = VirtualTextManager::new();
+
+        let id = manager.add(
+            &mut marker_list,
+            10,
+            ": i32".to_string(),
+            hint_style(),
+            VirtualTextPosition::AfterChar,
+            0,
+        );
+        assert!(manager.check_invariants(&marker_list, 20).is_ok());
+
+        let err = manager.check_invariants(&marker_list, 5).unwrap_err();
+        assert!(
+            err.contains(&format!("virtual text {} at 10", id.0)),
+            "{}",
+            err
+        );
+
+        let marker_id = manager.texts[&id].marker_id;
+        marker_list.delete(marker_id);
+        let err = manager.check_invariants(&marker_list, 20).unwrap_err();
+        assert!(err.contains("deleted marker"), "{}", err);
+    }
+
+    #[test]
+    fn test_clear_namespace() {
+        let mut marker_list = MarkerList::new();
+        let mut manager = VirtualTextManager::new();
+        let blame = VirtualTextNamespace::from_string("blame".to_string());
+        let other = VirtualTextNamespace::from_string("other".to_string());
+
+        let add_line = |manager: &mut VirtualTextManager,
+                        marker_list: &mut MarkerList,
+                        namespace: VirtualTextNamespace| {
+            manager.add_line(
+                marker_list,
+                0,
+                "header".to_string(),
+                hint_style(),
+                VirtualTextPosition::LineAbove,
+                namespace,
+                0,
+            )
+        };
+        let first = add_line(&mut manager, &mut marker_list, blame);
+        add_line(&mut manager, &mut marker_list, blame);
+        add_line(&mut manager, &mut marker_list, other);
+        manager.remove(&mut marker_list, first);
+
+        manager.clear_namespace(&mut marker_list, &blame);
+        assert_eq!(manager.len(), 1);
+        assert_eq!(marker_list.marker_count(), 1);
+        assert_eq!(
+            manager.texts.values().next().unwrap().namespace,
+            Some(other)
+        );
+        assert!(!manager.namespaces.contains_key(&blame));
+    }
 }
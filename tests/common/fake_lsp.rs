@@ -86,7 +86,7 @@ case "$method" in
         ;;
     "textDocument/completion")
         # Send completion response with sample items
-        send_message '{"jsonrpc":"2.0","id":'$msg_id',"result":{"items":[{"label":"test_function","kind":3,"detail":"fn test_function()","insertText":"test_function"},{"label":"test_variable","kind":6,"detail":"let test_variable","insertText":"test_variable"},{"label":"test_struct","kind":22,"detail":"struct TestStruct","insertText":"test_struct"}]}}'
+        send_message '{"jsonrpc":"2.0","id":'$msg_id',"result":{"isIncomplete":false,"items":[{"label":"test_function","kind":3,"detail":"fn test_function()","insertText":"test_function"},{"label":"test_variable","kind":6,"detail":"let test_variable","insertText":"test_variable"},{"label":"test_struct","kind":22,"detail":"struct TestStruct","insertText":"test_struct"}]}}'
         ;;
     "textDocument/definition")
         # Send definition response (points to line 0, col 0)
@@ -905,7 +905,7 @@ case "$method" in
         send_message '{"jsonrpc":"2.0","id":'$msg_id',"result":{"contents":{"kind":"markdown","value":"Test hover content"},"range":{"start":{"line":'$line',"character":'$char'},"end":{"line":'$line',"character":'$end_char'}}}}'
         ;;
     "textDocument/completion")
-        send_message '{"jsonrpc":"2.0","id":'$msg_id',"result":{"items":[{"label":"test_function","kind":3,"detail":"fn test_function()","insertText":"test_function"}]}}'
+        send_message '{"jsonrpc":"2.0","id":'$msg_id',"result":{"isIncomplete":false,"items":[{"label":"test_function","kind":3,"detail":"fn test_function()","insertText":"test_function"}]}}'
         ;;
     "textDocument/definition")
         uri=$(echo "$msg" | grep -o '"uri":"[^"]*"' | head -1 | cut -d'"' -f4)
@@ -915,7 +915,7 @@ case "$method" in
         # Notifications - no response needed
         ;;
     "textDocument/diagnostic")
-        send_message '{"jsonrpc":"2.0","id":'$msg_id',"result":{"items":[]}}'
+        send_message '{"jsonrpc":"2.0","id":'$msg_id',"result":{"isIncomplete":false,"items":[]}}'
         ;;
     "textDocument/inlayHint")
         send_message '{"jsonrpc":"2.0","id":'$msg_id',"result":[]}'
@@ -954,6 +954,99 @@ done
         std::env::temp_dir().join("fake_lsp_server_logging.sh")
     }
 
+    /// Spawn a fake LSP server that announces a position encoding and a
+    /// document sync kind, and logs the JSON of each didOpen and didChange
+    ///
+    /// The script takes the log file, the `positionEncoding` to announce
+    /// (such as `utf-8`) and the `textDocumentSync` kind (0, 1 or 2).
+    pub fn spawn_with_sync_log() -> std::io::Result<Self> {
+        let (stop_tx, stop_rx) = mpsc::channel();
+
+        let script = r#"#!/bin/bash
+
+LOG_FILE="$1"
+POSITION_ENCODING="$2"
+SYNC_KIND="$3"
+
+> "$LOG_FILE"
+
+read_message() {
+    local content_length=0
+    while IFS=: read -r key value; do
+        key=$(echo "$key" | tr -d '\r\n')
+        value=$(echo "$value" | tr -d '\r\n ')
+        if [ "$key" = "Content-Length" ]; then
+            content_length=$value
+        fi
+        if [ -z "$key" ]; then
+            break
+        fi
+    done
+
+    if [ $content_length -gt 0 ]; then
+        dd bs=1 count=$content_length 2>/dev/null
+    fi
+}
+
+send_message() {
+    local message="$1"
+    local length=${#message}
+    echo -en "Content-Length: $length\r\n\r\n$message"
+}
+
+while true; do
+    msg=$(read_message)
+
+    if [ -z "$msg" ]; then
+        break
+    fi
+
+    method=$(echo "$msg" | grep -o '"method":"[^"]*"' | cut -d'"' -f4)
+    msg_id=$(echo "$msg" | grep -o '"id":[0-9]*' | cut -d':' -f2)
+
+    case "$method" in
+        "initialize")
+            send_message '{"jsonrpc":"2.0","id":'$msg_id',"result":{"capabilities":{"positionEncoding":"'$POSITION_ENCODING'","textDocumentSync":'$SYNC_KIND'}}}'
+            ;;
+        "textDocument/didOpen"|"textDocument/didChange")
+            echo "$msg" >> "$LOG_FILE"
+            ;;
+        "shutdown")
+            send_message '{"jsonrpc":"2.0","id":'$msg_id',"result":null}'
+            break
+            ;;
+        *)
+            if [ -n "$msg_id" ]; then
+                send_message '{"jsonrpc":"2.0","id":'$msg_id',"result":null}'
+            fi
+            ;;
+    esac
+done
+"#;
+
+        let script_path = Self::sync_log_script_path();
+        std::fs::write(&script_path, script)?;
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mut perms = std::fs::metadata(&script_path)?.permissions();
+            perms.set_mode(0o755);
+            std::fs::set_permissions(&script_path, perms)?;
+        }
+
+        let handle = Some(thread::spawn(move || {
+            let _ = stop_rx.recv();
+        }));
+
+        Ok(Self { handle, stop_tx })
+    }
+
+    /// Get the path to the sync logging fake LSP server script
+    pub fn sync_log_script_path() -> std::path::PathBuf {
+        std::env::temp_dir().join("fake_lsp_server_sync_log.sh")
+    }
+
     /// Get the default log file path used by the logging server
     pub fn default_log_path() -> std::path::PathBuf {
         std::path::PathBuf::from("/tmp/fake_lsp_log.txt")
@@ -7,7 +7,7 @@
 #[cfg(feature = "plugins")]
 #[ctor::ctor]
 fn init_v8_for_tests() {
-    fresh::v8_init::init();
+    fresh::v8_init::init().expect("V8 platform initialization failed");
 }
 
 // Common initialization (non-plugin related)
@@ -409,6 +409,15 @@ impl EditorTestHarness {
             Some(time_source),
         )?;
 
+        // Tests expect file contents right after open_file and files written
+        // right after saving; tests of background loading and saving lower
+        // these again
+        editor.set_quick_load_timeout(std::time::Duration::from_secs(30));
+        editor.set_quick_save_timeout(std::time::Duration::from_secs(30));
+
+        // Check the model after every event so corruptions fail at their cause
+        editor.set_validate_invariants(true);
+
         // Process any pending plugin commands
         editor.process_async_messages();
 
@@ -467,7 +476,11 @@ impl EditorTestHarness {
 
     /// Create with explicit working directory, loading config from that directory.
     pub fn with_working_dir(width: u16, height: u16, working_dir: PathBuf) -> io::Result<Self> {
-        let config = Config::load_for_working_dir(&working_dir);
+        // Only the project's own config; the user config dir doesn't exist
+        let config = Config::load_for_working_dir(
+            &DirectoryContext::for_testing(&working_dir),
+            &working_dir,
+        );
         Self::with_config_and_working_dir(width, height, config, working_dir)
     }
 
@@ -577,12 +590,12 @@ impl EditorTestHarness {
             .map(|d| d.path().join("project_root"))
     }
 
-    /// Get the recovery directory path for this test harness
+    /// Get the recovery directory path for this test harness's project
     /// The recovery directory is isolated per-test under the temp directory
     pub fn recovery_dir(&self) -> Option<PathBuf> {
         self._temp_dir
             .as_ref()
-            .map(|d| d.path().join("data").join("recovery"))
+            .map(|_| self.editor().recovery_dir().to_path_buf())
     }
 
     /// Take ownership of the temp directory, preventing it from being cleaned up
@@ -593,6 +606,40 @@ impl EditorTestHarness {
         self._temp_dir.take()
     }
 
+    /// Switch to the project the editor asked to restart in, as the main loop
+    /// does: the state carried across the switch is taken from the editor,
+    /// which is replaced by one in the new working directory sharing
+    /// `dir_context`. The new editor restores the project's session, then the
+    /// carried state. Returns the new working directory.
+    pub fn switch_project(&mut self, dir_context: DirectoryContext) -> io::Result<PathBuf> {
+        let new_dir = self
+            .editor
+            .take_restart_dir()
+            .ok_or_else(|| io::Error::other("no project switch was requested"))?;
+        let carry_over = self.editor.take_carry_over();
+        self.editor.release_session_ownership();
+        self.editor.shutdown_lsp_servers();
+
+        let config = self.editor.config().clone();
+        let mut next = Self::with_shared_dir_context(
+            self.term_width,
+            self.term_height,
+            config,
+            new_dir.clone(),
+            dir_context,
+        )?;
+        if next.editor.claim_session_ownership(true) {
+            next.editor
+                .try_restore_session()
+                .map_err(|e| io::Error::other(e.to_string()))?;
+        }
+        next.editor.restore_carry_over(carry_over);
+        next._temp_dir = self._temp_dir.take();
+        *self = next;
+        self.render()?;
+        Ok(new_dir)
+    }
+
     /// Enable shadow buffer validation
     /// Call this at the start of tests that focus on simple text editing operations
     /// where you want to validate that the piece tree matches simple string operations
@@ -700,11 +747,21 @@ impl EditorTestHarness {
 
     /// Simulate a mouse click at specific coordinates
     pub fn mouse_click(&mut self, col: u16, row: u16) -> io::Result<()> {
+        self.mouse_click_with_modifiers(col, row, KeyModifiers::empty())
+    }
+
+    /// Simulate a mouse click with modifier keys held (e.g. Ctrl+Click)
+    pub fn mouse_click_with_modifiers(
+        &mut self,
+        col: u16,
+        row: u16,
+        modifiers: KeyModifiers,
+    ) -> io::Result<()> {
         let mouse_event = MouseEvent {
             kind: MouseEventKind::Down(MouseButton::Left),
             column: col,
             row,
-            modifiers: KeyModifiers::empty(),
+            modifiers,
         };
         self.send_mouse(mouse_event)?;
 
@@ -713,7 +770,7 @@ impl EditorTestHarness {
             kind: MouseEventKind::Up(MouseButton::Left),
             column: col,
             row,
-            modifiers: KeyModifiers::empty(),
+            modifiers,
         };
         self.send_mouse(mouse_up)?;
         self.render()?;
@@ -759,6 +816,32 @@ impl EditorTestHarness {
         Ok(())
     }
 
+    /// Simulate a horizontal mouse scroll left at specific coordinates
+    pub fn mouse_scroll_left(&mut self, col: u16, row: u16) -> io::Result<()> {
+        let mouse_event = MouseEvent {
+            kind: MouseEventKind::ScrollLeft,
+            column: col,
+            row,
+            modifiers: KeyModifiers::empty(),
+        };
+        self.send_mouse(mouse_event)?;
+        self.render()?;
+        Ok(())
+    }
+
+    /// Simulate a horizontal mouse scroll right at specific coordinates
+    pub fn mouse_scroll_right(&mut self, col: u16, row: u16) -> io::Result<()> {
+        let mouse_event = MouseEvent {
+            kind: MouseEventKind::ScrollRight,
+            column: col,
+            row,
+            modifiers: KeyModifiers::empty(),
+        };
+        self.send_mouse(mouse_event)?;
+        self.render()?;
+        Ok(())
+    }
+
     /// Simulate a mouse drag from one position to another
     pub fn mouse_drag(
         &mut self,
@@ -766,13 +849,31 @@ impl EditorTestHarness {
         start_row: u16,
         end_col: u16,
         end_row: u16,
+    ) -> io::Result<()> {
+        self.mouse_drag_with_modifiers(
+            start_col,
+            start_row,
+            end_col,
+            end_row,
+            KeyModifiers::empty(),
+        )
+    }
+
+    /// Simulate a mouse drag with modifier keys held (e.g. Alt+Drag)
+    pub fn mouse_drag_with_modifiers(
+        &mut self,
+        start_col: u16,
+        start_row: u16,
+        end_col: u16,
+        end_row: u16,
+        modifiers: KeyModifiers,
     ) -> io::Result<()> {
         // Send initial press
         let mouse_down = MouseEvent {
             kind: MouseEventKind::Down(MouseButton::Left),
             column: start_col,
             row: start_row,
-            modifiers: KeyModifiers::empty(),
+            modifiers,
         };
         self.send_mouse(mouse_down)?;
 
@@ -789,7 +890,7 @@ impl EditorTestHarness {
                 kind: MouseEventKind::Drag(MouseButton::Left),
                 column: col as u16,
                 row: row as u16,
-                modifiers: KeyModifiers::empty(),
+                modifiers,
             };
             self.send_mouse(mouse_drag_event)?;
         }
@@ -799,7 +900,7 @@ impl EditorTestHarness {
             kind: MouseEventKind::Up(MouseButton::Left),
             column: end_col,
             row: end_row,
-            modifiers: KeyModifiers::empty(),
+            modifiers,
         };
         self.send_mouse(mouse_up)?;
         self.render()?;
@@ -1489,6 +1590,12 @@ impl EditorTestHarness {
         Ok(())
     }
 
+    /// Resize the virtual terminal without notifying the editor
+    /// Simulates a terminal that drops resize events
+    pub fn resize_backend_only(&mut self, width: u16, height: u16) {
+        self.terminal.backend_mut().resize(width, height);
+    }
+
     /// Process pending async messages (including file polling) and render
     /// Useful for testing async features like git grep, file explorer, auto-revert, etc.
     pub fn process_async_and_render(&mut self) -> io::Result<()> {
@@ -1558,11 +1665,14 @@ impl EditorTestHarness {
                 return false;
             }
             let screen = h.screen_to_string();
-            // Look for the item in a file explorer tree line (contains │ tree connector)
-            // or in a line with tree markers like > or ▼
+            // Look for the item in a file explorer tree line (contains │ tree connector,
+            // or | in ASCII-only mode) or in a line with tree markers like > or ▼
             screen.lines().any(|line| {
                 line.contains(&name)
-                    && (line.contains("│") || line.contains(">") || line.contains("▼"))
+                    && (line.contains("│")
+                        || line.contains('|')
+                        || line.contains(">")
+                        || line.contains("▼"))
             })
         })
     }
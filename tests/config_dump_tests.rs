@@ -0,0 +1,214 @@
+// Tests for the `--dump-*` command line flags
+//
+// The JSON shapes are compared against golden files in tests/fixtures/dump,
+// since tools outside the editor rely on them.
+
+use fresh::config_dump;
+use fresh::config_io::DirectoryContext;
+use fresh::input::keybindings::KeyContext;
+use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use tempfile::TempDir;
+
+fn fixture(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures/dump")
+        .join(name)
+}
+
+fn golden(name: &str) -> Value {
+    serde_json::from_str(&std::fs::read_to_string(fixture(name)).unwrap()).unwrap()
+}
+
+/// Type name of each field of an object
+fn shape(value: &Value) -> Value {
+    let type_name = |value: &Value| match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    };
+    let object = value.as_object().expect("expected an object");
+    Value::Object(
+        object
+            .iter()
+            .map(|(key, value)| (key.clone(), json!(type_name(value))))
+            .collect(),
+    )
+}
+
+/// Field names of an object, sorted
+fn field_names(value: &Value) -> Vec<String> {
+    let mut names: Vec<String> = value.as_object().unwrap().keys().cloned().collect();
+    names.sort();
+    names
+}
+
+/// Strings of a golden array, sorted
+fn sorted_strings(value: &Value) -> Vec<String> {
+    let mut strings: Vec<String> = value
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|s| s.as_str().unwrap().to_string())
+        .collect();
+    strings.sort();
+    strings
+}
+
+fn load_fixture_config(temp_dir: &TempDir) -> fresh::config::Config {
+    let dir_context = DirectoryContext::for_testing(temp_dir.path());
+    config_dump::load_config(
+        &dir_context,
+        temp_dir.path(),
+        Some(&fixture("user_config.json")),
+    )
+    .unwrap()
+}
+
+#[test]
+fn test_dump_config_matches_golden_shape() {
+    let temp_dir = TempDir::new().unwrap();
+    let config = load_fixture_config(&temp_dir);
+    let dumped = config_dump::effective_config(&config).unwrap();
+
+    assert_eq!(shape(&dumped), golden("config_shape.json"));
+    // User settings are merged over the defaults
+    assert_eq!(dumped["theme"], "dracula");
+    assert_eq!(dumped["editor"]["tab_size"], 2);
+    assert_eq!(dumped["editor"]["auto_indent"], true);
+}
+
+#[test]
+fn test_dump_config_user_is_the_file_as_written() {
+    let temp_dir = TempDir::new().unwrap();
+    let dir_context = DirectoryContext::for_testing(temp_dir.path());
+
+    let dumped = config_dump::user_config(
+        &dir_context,
+        temp_dir.path(),
+        Some(&fixture("user_config.json")),
+    )
+    .unwrap();
+    assert_eq!(dumped, golden("user_config.json"));
+
+    // Without a config file there are no user settings
+    let dumped = config_dump::user_config(&dir_context, temp_dir.path(), None).unwrap();
+    assert_eq!(dumped, json!({}));
+}
+
+#[test]
+fn test_dump_keymap_matches_golden_shape() {
+    let temp_dir = TempDir::new().unwrap();
+    let config = load_fixture_config(&temp_dir);
+    let expected = golden("keymap_shape.json");
+
+    let dumped = config_dump::keymap(&config, None);
+    assert_eq!(field_names(&dumped), sorted_strings(&expected["contexts"]));
+    let binding_fields = sorted_strings(&expected["binding_fields"]);
+    let shadowed_fields = sorted_strings(&expected["shadowed_fields"]);
+    for bindings in dumped.as_object().unwrap().values() {
+        for binding in bindings.as_array().unwrap() {
+            assert_eq!(field_names(binding), binding_fields);
+            for shadowed in binding["shadowed"].as_array().unwrap() {
+                assert_eq!(field_names(shadowed), shadowed_fields);
+            }
+        }
+    }
+
+    // One context only, with the user's binding and its provenance
+    let dumped = config_dump::keymap(&config, Some(KeyContext::Normal));
+    assert_eq!(field_names(&dumped), vec!["normal".to_string()]);
+    let normal = dumped["normal"].as_array().unwrap();
+    let user = normal
+        .iter()
+        .find(|b| b["source"] == "user")
+        .expect("user binding listed");
+    assert_eq!(user["action"], "save");
+    assert!(user["key"].as_str().unwrap().ends_with('K'));
+    assert!(normal
+        .iter()
+        .any(|b| b["source"] == "keymap" && b["keymap"].is_string()));
+}
+
+#[test]
+fn test_dump_schema_matches_golden_shape() {
+    let temp_dir = TempDir::new().unwrap();
+    let schema = config_dump::schema();
+    assert_eq!(shape(&schema), golden("schema_shape.json"));
+
+    // Every setting of the dumped config is described by the schema
+    let config = config_dump::effective_config(&load_fixture_config(&temp_dir)).unwrap();
+    assert_eq!(field_names(&schema["properties"]), field_names(&config));
+}
+
+#[test]
+fn test_invalid_config_is_an_error() {
+    let temp_dir = TempDir::new().unwrap();
+    let dir_context = DirectoryContext::for_testing(temp_dir.path());
+    let config_path = dir_context.config_path();
+    std::fs::create_dir_all(config_path.parent().unwrap()).unwrap();
+    std::fs::write(&config_path, "{ \"editor\": { \"tab_size\": \"two\" } }").unwrap();
+
+    // Found in the usual place: an error naming the file, not the defaults
+    let error = config_dump::load_config(&dir_context, temp_dir.path(), None).unwrap_err();
+    assert!(error
+        .to_string()
+        .contains(&config_path.display().to_string()));
+    assert!(config_dump::user_config(&dir_context, temp_dir.path(), None).is_err());
+
+    // Given with --config
+    let error =
+        config_dump::load_config(&dir_context, temp_dir.path(), Some(&config_path)).unwrap_err();
+    assert!(error.to_string().contains("Parse error"));
+}
+
+#[test]
+fn test_dump_flags_exit_status() {
+    let temp_dir = TempDir::new().unwrap();
+    let bad_config = temp_dir.path().join("bad.json");
+    std::fs::write(&bad_config, "{ not json").unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_fresh"))
+        .arg("--dump-config")
+        .arg("--config")
+        .arg(&bad_config)
+        .current_dir(temp_dir.path())
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("bad.json"));
+    assert!(output.stdout.is_empty());
+
+    let output = Command::new(env!("CARGO_BIN_EXE_fresh"))
+        .arg("--dump-keymap")
+        .arg("prompt")
+        .arg("--config")
+        .arg(fixture("user_config.json"))
+        .current_dir(temp_dir.path())
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let keymap: Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert!(keymap["prompt"].is_array());
+
+    let output = Command::new(env!("CARGO_BIN_EXE_fresh"))
+        .arg("--dump-keymap")
+        .arg("nowhere")
+        .arg("--config")
+        .arg(fixture("user_config.json"))
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+
+    let output = Command::new(env!("CARGO_BIN_EXE_fresh"))
+        .arg("--dump-schema")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let schema: Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(schema["title"], "Config");
+}
@@ -0,0 +1,41 @@
+//! E2E tests for drawing ambiguous-width characters with `editor.ambiguous_width`
+use crate::common::harness::EditorTestHarness;
+use fresh::config::{AmbiguousWidth, Config};
+
+/// Column and row of the first cell showing `symbol`
+fn find_cell(harness: &EditorTestHarness, symbol: &str) -> (u16, u16) {
+    let area = harness.buffer().area;
+    (area.top()..area.bottom())
+        .flat_map(|y| (area.left()..area.right()).map(move |x| (x, y)))
+        .find(|&(x, y)| harness.get_cell(x, y).as_deref() == Some(symbol))
+        .unwrap_or_else(|| panic!("'{symbol}' not on screen:\n{}", harness.screen_to_string()))
+}
+
+/// In wide mode ambiguous characters take two cells on screen, and the text
+/// after them and the cursor line up with the editor's layout
+#[test]
+fn test_wide_ambiguous_characters_take_two_cells() {
+    let mut config = Config::default();
+    config.editor.ambiguous_width = AmbiguousWidth::Wide;
+    let mut harness = EditorTestHarness::with_config(80, 24, config).unwrap();
+    harness.type_text("①②x").unwrap();
+    harness.render().unwrap();
+
+    let (x, y) = find_cell(&harness, "①");
+    assert_eq!(harness.get_cell(x + 2, y).as_deref(), Some("②"));
+    assert_eq!(harness.get_cell(x + 4, y).as_deref(), Some("x"));
+    assert_eq!(harness.screen_cursor_position(), (x + 5, y));
+}
+
+/// In narrow mode they take one cell each
+#[test]
+fn test_narrow_ambiguous_characters_take_one_cell() {
+    let mut harness = EditorTestHarness::new(80, 24).unwrap();
+    harness.type_text("①②x").unwrap();
+    harness.render().unwrap();
+
+    let (x, y) = find_cell(&harness, "①");
+    assert_eq!(harness.get_cell(x + 1, y).as_deref(), Some("②"));
+    assert_eq!(harness.get_cell(x + 2, y).as_deref(), Some("x"));
+    assert_eq!(harness.screen_cursor_position(), (x + 3, y));
+}
@@ -0,0 +1,172 @@
+//! E2E tests for popups plugins anchor to buffer text
+
+use crate::common::harness::EditorTestHarness;
+use crossterm::event::{KeyCode, KeyModifiers};
+use fresh::model::event::{PopupContentData, PopupData, PopupPositionData};
+use fresh::services::plugins::api::PluginCommand;
+use std::time::Duration;
+
+/// Lines of the form "row 07 #c07", each 12 bytes with the newline
+fn numbered_lines(count: usize) -> String {
+    (0..count)
+        .map(|i| format!("row {i:02} #c{i:02}\n"))
+        .collect()
+}
+
+/// Byte offset of the "#" on line `line` of `numbered_lines`
+fn color_position(line: usize) -> usize {
+    line * 12 + 7
+}
+
+fn show_anchored(harness: &mut EditorTestHarness, position: usize, lines: &[&str]) {
+    let buffer_id = harness.editor().active_buffer();
+    harness
+        .editor_mut()
+        .handle_plugin_command(PluginCommand::ShowAnchoredPopup {
+            buffer_id,
+            position,
+            lines: lines.iter().map(|line| line.to_string()).collect(),
+            max_width: 40,
+            timeout_ms: None,
+        })
+        .unwrap();
+    harness.render().unwrap();
+}
+
+/// Screen row showing `text`
+fn row_of(harness: &EditorTestHarness, text: &str) -> usize {
+    (0..harness.terminal_height())
+        .find(|row| harness.get_screen_row(*row).contains(text))
+        .unwrap_or_else(|| panic!("{text} not on screen:\n{}", harness.screen_to_string()))
+}
+
+/// The popup goes below its anchor's line, and above it when the anchor is
+/// on the last line of the screen
+#[test]
+fn test_anchored_popup_flips_above_near_the_bottom_edge() {
+    let mut harness = EditorTestHarness::new(60, 14).unwrap();
+    let _fixture = harness.load_buffer_from_text(&numbered_lines(40)).unwrap();
+    harness.render().unwrap();
+
+    let anchor_row = row_of(&harness, "row 01");
+    show_anchored(
+        &mut harness,
+        color_position(1),
+        &["Swatch one", "Swatch two"],
+    );
+    assert!(harness.editor().has_anchored_popup());
+    assert!(harness.get_screen_row(anchor_row).contains("row 01"));
+    assert!(harness
+        .get_screen_row(anchor_row + 1)
+        .contains("Swatch one"));
+    assert!(harness
+        .get_screen_row(anchor_row + 2)
+        .contains("Swatch two"));
+
+    // The last line on screen
+    let (first, last) = harness.content_area_rows();
+    let bottom_row = (first..=last)
+        .rev()
+        .find(|row| harness.get_screen_row(*row).contains("row "))
+        .unwrap();
+    let bottom_text = harness.get_screen_row(bottom_row);
+    let line: usize = bottom_text
+        .split("row ")
+        .nth(1)
+        .and_then(|rest| rest.get(..2))
+        .and_then(|number| number.parse().ok())
+        .unwrap();
+    show_anchored(
+        &mut harness,
+        color_position(line),
+        &["Swatch one", "Swatch two", "Swatch three"],
+    );
+    assert!(harness
+        .get_screen_row(bottom_row)
+        .contains(&format!("row {line:02}")));
+    assert!(harness
+        .get_screen_row(bottom_row - 3)
+        .contains("Swatch one"));
+    assert!(harness
+        .get_screen_row(bottom_row - 1)
+        .contains("Swatch three"));
+}
+
+/// A newer anchored popup replaces the older one, and moving the cursor or
+/// the mouse off the word, or the timeout, closes it
+#[test]
+fn test_anchored_popup_closes_when_moving_away_or_timing_out() {
+    let mut harness = EditorTestHarness::new(60, 14).unwrap();
+    let _fixture = harness.load_buffer_from_text(&numbered_lines(10)).unwrap();
+    harness.render().unwrap();
+
+    show_anchored(&mut harness, color_position(1), &["First popup"]);
+    show_anchored(&mut harness, color_position(2), &["Second popup"]);
+    harness.assert_screen_not_contains("First popup");
+    harness.assert_screen_contains("Second popup");
+
+    // Cursor moves to another line
+    harness.send_key(KeyCode::Down, KeyModifiers::NONE).unwrap();
+    harness.process_async_and_render().unwrap();
+    harness.assert_screen_not_contains("Second popup");
+    assert!(!harness.editor().has_anchored_popup());
+
+    // Mouse moves to another line
+    show_anchored(&mut harness, color_position(4), &["Third popup"]);
+    let row = row_of(&harness, "row 07");
+    harness.mouse_move(10, row as u16).unwrap();
+    harness.render().unwrap();
+    harness.assert_screen_not_contains("Third popup");
+
+    // Timeout
+    let buffer_id = harness.editor().active_buffer();
+    harness
+        .editor_mut()
+        .handle_plugin_command(PluginCommand::ShowAnchoredPopup {
+            buffer_id,
+            position: color_position(5),
+            lines: vec!["Fourth popup".to_string()],
+            max_width: 40,
+            timeout_ms: Some(500),
+        })
+        .unwrap();
+    harness.process_async_and_render().unwrap();
+    harness.assert_screen_contains("Fourth popup");
+    harness.advance_time(Duration::from_millis(600));
+    harness.process_async_and_render().unwrap();
+    harness.assert_screen_not_contains("Fourth popup");
+}
+
+/// A hover popup takes precedence over a plugin's anchored popup
+#[test]
+fn test_hover_popup_takes_precedence_over_anchored_popup() {
+    let mut harness = EditorTestHarness::new(60, 14).unwrap();
+    let _fixture = harness.load_buffer_from_text(&numbered_lines(10)).unwrap();
+    harness.render().unwrap();
+    let hover = PopupData {
+        title: Some("Hover".to_string()),
+        description: None,
+        transient: true,
+        content: PopupContentData::Text(vec!["fn documented()".to_string()]),
+        position: PopupPositionData::BelowCursor,
+        width: 40,
+        max_height: 10,
+        bordered: true,
+    };
+
+    // Shown while the hover is open: ignored
+    harness.editor_mut().show_popup(hover.clone());
+    show_anchored(&mut harness, color_position(1), &["Anchored"]);
+    assert!(!harness.editor().has_anchored_popup());
+    harness.assert_screen_contains("fn documented()");
+    harness.assert_screen_not_contains("Anchored");
+    harness.editor_mut().hide_popup();
+
+    // Hover opened on top of it: only the hover shows
+    show_anchored(&mut harness, color_position(1), &["Anchored"]);
+    harness.assert_screen_contains("Anchored");
+    harness.editor_mut().show_popup(hover);
+    harness.render().unwrap();
+    harness.assert_screen_contains("fn documented()");
+    harness.assert_screen_not_contains("Anchored");
+}
@@ -0,0 +1,115 @@
+//! Tests for showing ANSI colors in log files and plugin virtual buffers
+
+use crate::common::harness::EditorTestHarness;
+use fresh::primitives::text_property::TextPropertyEntry;
+use fresh::services::plugins::api::PluginCommand;
+use ratatui::style::Color;
+use std::collections::HashMap;
+use tempfile::TempDir;
+
+/// Cargo-style output: a bold red "error" followed by plain text
+const CARGO_OUTPUT: &str =
+    "\x1b[1m\x1b[31merror\x1b[0m: expected `;`\n\x1b[32m   Compiling\x1b[0m fresh v0.1.0\n";
+
+/// Screen position of the first occurrence of `text`
+fn find_on_screen(harness: &EditorTestHarness, text: &str) -> (u16, u16) {
+    harness
+        .screen_to_string()
+        .lines()
+        .enumerate()
+        .find_map(|(row, line)| {
+            line.find(text)
+                .map(|byte| (line[..byte].chars().count() as u16, row as u16))
+        })
+        .unwrap_or_else(|| panic!("{text:?} not on screen"))
+}
+
+#[test]
+fn test_toggle_ansi_colors_strips_escapes_and_colors_text() {
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join("build.log");
+    std::fs::write(&path, CARGO_OUTPUT).unwrap();
+
+    let mut harness = EditorTestHarness::new(80, 24).unwrap();
+    harness.open_file(&path).unwrap();
+    harness.editor_mut().toggle_ansi_colors();
+    harness.render().unwrap();
+
+    assert_eq!(
+        harness.get_buffer_content().unwrap(),
+        "error: expected `;`\n   Compiling fresh v0.1.0\n"
+    );
+    assert!(harness.editor().is_editing_disabled());
+    harness.assert_screen_not_contains("[31m");
+
+    let (x, y) = find_on_screen(&harness, "error:");
+    let style = harness.get_cell_style(x, y).unwrap();
+    assert_eq!(style.fg, Some(Color::Red));
+    let (x, y) = find_on_screen(&harness, "Compiling");
+    assert_eq!(harness.get_cell_style(x, y).unwrap().fg, Some(Color::Green));
+    // Text after a reset keeps the theme's color
+    let (x, y) = find_on_screen(&harness, "expected");
+    assert_ne!(harness.get_cell_style(x, y).unwrap().fg, Some(Color::Red));
+
+    // Turning it off puts the escape sequences back
+    harness.editor_mut().toggle_ansi_colors();
+    assert_eq!(harness.get_buffer_content().unwrap(), CARGO_OUTPUT);
+    assert!(!harness.editor().is_editing_disabled());
+}
+
+#[test]
+fn test_save_blocked_while_showing_ansi_colors() {
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join("build.log");
+    std::fs::write(&path, CARGO_OUTPUT).unwrap();
+
+    let mut harness = EditorTestHarness::new(80, 24).unwrap();
+    harness.open_file(&path).unwrap();
+    harness.editor_mut().toggle_ansi_colors();
+    harness.editor_mut().save().unwrap();
+
+    // The file on disk keeps its escape sequences
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), CARGO_OUTPUT);
+    assert!(harness
+        .editor()
+        .get_status_message()
+        .is_some_and(|msg| msg.contains("ANSI")));
+}
+
+#[test]
+fn test_virtual_buffer_parses_ansi_colors() {
+    let mut harness = EditorTestHarness::new(80, 24).unwrap();
+    let entry = |text: &str| TextPropertyEntry {
+        text: text.to_string(),
+        properties: HashMap::new(),
+    };
+    // The color set in the first entry carries over into the second
+    let cmd = PluginCommand::CreateVirtualBufferWithContent {
+        name: "*Build*".to_string(),
+        mode: "build".to_string(),
+        read_only: true,
+        entries: vec![entry("\x1b[33mwarning"), entry(": unused\x1b[0m done\n")],
+        show_line_numbers: false,
+        show_cursors: true,
+        editing_disabled: true,
+        parse_ansi: true,
+        request_id: None,
+    };
+    harness.editor_mut().handle_plugin_command(cmd).unwrap();
+    harness.render().unwrap();
+
+    assert_eq!(
+        harness.get_buffer_content().unwrap(),
+        "warning: unused done\n"
+    );
+    let (x, y) = find_on_screen(&harness, "unused");
+    assert_eq!(
+        harness.get_cell_style(x, y).unwrap().fg,
+        Some(Color::Yellow)
+    );
+    let (x, y) = find_on_screen(&harness, "done");
+    assert_ne!(
+        harness.get_cell_style(x, y).unwrap().fg,
+        Some(Color::Yellow)
+    );
+}
@@ -0,0 +1,150 @@
+//! E2E tests for `ui.ascii_only`: chrome is drawn with ASCII fallbacks only
+
+use crate::common::harness::EditorTestHarness;
+use crossterm::event::{KeyCode, KeyModifiers};
+use fresh::config::Config;
+use std::fs;
+
+fn ascii_config() -> Config {
+    let mut config = Config::default();
+    config.ui.ascii_only = Some(true);
+    config
+}
+
+/// Characters on screen outside ASCII, in order of first appearance
+fn non_ascii(screen: &str) -> Vec<char> {
+    let mut found = Vec::new();
+    for c in screen.chars().filter(|c| !c.is_ascii()) {
+        if !found.contains(&c) {
+            found.push(c);
+        }
+    }
+    found
+}
+
+fn assert_all_ascii(screen: &str) {
+    assert!(
+        non_ascii(screen).is_empty(),
+        "ASCII-only screen has {:?}:\n{}",
+        non_ascii(screen),
+        screen
+    );
+}
+
+#[test]
+fn test_ascii_only_file_explorer() {
+    let mut harness =
+        EditorTestHarness::with_temp_project_and_config(80, 20, ascii_config()).unwrap();
+    let project_root = harness.project_dir().unwrap();
+    fs::create_dir(project_root.join("src")).unwrap();
+    fs::write(project_root.join("src/main.rs"), "fn main() {}").unwrap();
+    fs::write(project_root.join("notes.txt"), "notes").unwrap();
+
+    harness.editor_mut().focus_file_explorer();
+    harness.wait_for_file_explorer().unwrap();
+    harness.wait_for_file_explorer_item("notes.txt").unwrap();
+
+    harness.send_key(KeyCode::Down, KeyModifiers::NONE).unwrap();
+    harness.render().unwrap();
+    let screen = harness.screen_to_string();
+    assert!(screen.contains("> src"), "{}", screen);
+    assert_all_ascii(&screen);
+
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    harness.wait_for_file_explorer_item("main.rs").unwrap();
+
+    let screen = harness.screen_to_string();
+    assert!(screen.contains("v src"), "{}", screen);
+    assert!(screen.contains("main.rs"), "{}", screen);
+
+    // The panel border uses +, - and |
+    let title_row = screen
+        .lines()
+        .find(|line| line.contains("File Explorer"))
+        .expect("explorer title row");
+    assert!(title_row.starts_with('+'), "{}", title_row);
+    assert!(title_row.contains("-"), "{}", title_row);
+    let file_row = screen
+        .lines()
+        .find(|line| line.contains("notes.txt"))
+        .unwrap();
+    assert!(file_row.starts_with('|'), "{}", file_row);
+    assert_all_ascii(&screen);
+}
+
+#[test]
+fn test_ascii_only_settings_map_control() {
+    let mut harness = EditorTestHarness::with_config(100, 40, ascii_config()).unwrap();
+
+    harness
+        .send_key(KeyCode::Char(','), KeyModifiers::CONTROL)
+        .unwrap();
+    harness
+        .send_key(KeyCode::Char('/'), KeyModifiers::NONE)
+        .unwrap();
+    harness.type_text("languages").unwrap();
+    harness.render().unwrap();
+    // The search field icon and cursor are ASCII too
+    harness.assert_screen_contains("/ languages_");
+
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    harness.render().unwrap();
+
+    let screen = harness.screen_to_string();
+    // The focused map keeps its ">" marker, its entries listed below
+    assert!(screen.contains("> Languages:"), "{}", screen);
+    assert!(screen.contains("bash"), "{}", screen);
+    // Settings dialog border, drawn over the editor's gutter
+    assert!(
+        screen.lines().any(|line| line.contains("+-----")),
+        "{}",
+        screen
+    );
+    assert_all_ascii(&screen);
+}
+
+#[test]
+fn test_ascii_only_split_borders() {
+    let mut harness = EditorTestHarness::with_config(80, 24, ascii_config()).unwrap();
+    harness.type_text("left and right").unwrap();
+
+    harness
+        .send_key(KeyCode::Char('p'), KeyModifiers::CONTROL)
+        .unwrap();
+    harness.type_text("split vert").unwrap();
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    harness.render().unwrap();
+
+    let screen = harness.screen_to_string();
+    let rows: Vec<&str> = screen.lines().collect();
+    // The separator is a column of "|" through the middle of the editor rows
+    let column = (1..80).find(|&x| {
+        rows[2..20]
+            .iter()
+            .all(|row| row.chars().nth(x) == Some('|'))
+    });
+    assert!(column.is_some(), "no vertical separator:\n{}", screen);
+    assert_eq!(screen.matches("left and right").count(), 2, "{}", screen);
+    assert_all_ascii(&screen);
+}
+
+#[test]
+fn test_unicode_glyphs_by_default() {
+    let mut harness = EditorTestHarness::new(80, 24).unwrap();
+    harness
+        .send_key(KeyCode::Char('p'), KeyModifiers::CONTROL)
+        .unwrap();
+    harness.type_text("split vert").unwrap();
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    harness.render().unwrap();
+
+    harness.assert_screen_contains("│");
+}
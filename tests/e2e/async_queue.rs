@@ -0,0 +1,89 @@
+//! Floods of async messages are handled in budgeted batches between input
+
+use crate::common::harness::EditorTestHarness;
+use crossterm::event::{KeyCode, KeyModifiers};
+use fresh::services::async_bridge::AsyncMessage;
+use fresh::services::async_queue::MESSAGE_TIME_BUDGET;
+use lsp_types::Diagnostic;
+use std::time::{Duration, Instant};
+
+const FLOOD: usize = 50_000;
+
+/// Time a call may take beyond the budget: the message that crossed it and
+/// the rest of the loop's polling, with room for slow debug builds sharing
+/// the machine with the rest of the suite
+const SLACK: Duration = Duration::from_millis(200);
+
+fn diagnostic(version: usize) -> Diagnostic {
+    Diagnostic {
+        message: format!("version {}", version),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_file_event_flood_does_not_starve_input() {
+    let mut harness = EditorTestHarness::new(80, 24).unwrap();
+    let sender = harness.editor().async_bridge().unwrap().sender();
+    let uri = "file:///tmp/flood/main.rs";
+    let mut last_version = 0;
+    for i in 0..FLOOD {
+        sender
+            .send(AsyncMessage::FileChanged {
+                path: format!("/tmp/flood/file{}.rs", i % 500),
+            })
+            .unwrap();
+        if i % 10 == 0 {
+            sender
+                .send(AsyncMessage::LspDiagnostics {
+                    language: "rust".to_string(),
+                    uri: uri.to_string(),
+                    diagnostics: vec![diagnostic(i)],
+                })
+                .unwrap();
+            last_version = i;
+        }
+    }
+
+    // The first call takes the flood off the channel and coalesces it
+    harness.editor_mut().process_async_messages();
+    assert!(harness.editor().has_queued_async_messages());
+    let stats = harness.editor().async_queue_stats();
+    assert!(stats.coalesced > 0);
+    assert_eq!(stats.dropped, 0);
+
+    // Keys typed while the backlog drains are handled between batches
+    let mut typed = String::new();
+    let mut keys = "hello world".chars();
+    let mut calls = 0;
+    while harness.editor().has_queued_async_messages() {
+        if let Some(ch) = keys.next() {
+            harness
+                .editor_mut()
+                .handle_key(KeyCode::Char(ch), KeyModifiers::NONE)
+                .unwrap();
+            typed.push(ch);
+            assert_eq!(harness.get_buffer_content().unwrap(), typed);
+        }
+
+        let started = Instant::now();
+        harness.editor_mut().process_async_messages();
+        assert!(
+            started.elapsed() < MESSAGE_TIME_BUDGET + SLACK,
+            "handling queued messages took {:?}",
+            started.elapsed()
+        );
+        calls += 1;
+        assert!(calls < FLOOD, "queue never drained");
+    }
+    assert!(calls > 1, "the backlog should take several iterations");
+
+    // Coalescing kept only the newest diagnostics for the URI
+    let stored = harness.editor().get_stored_diagnostics();
+    let diagnostics = &stored
+        .get(uri)
+        .expect("diagnostics should be stored")
+        .diagnostics;
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].message, format!("version {}", last_version));
+}
@@ -0,0 +1,268 @@
+//! E2E tests for the completion popup opening by itself while typing
+
+use crate::common::fake_lsp::FakeLspServer;
+use crate::common::harness::EditorTestHarness;
+use crossterm::event::{KeyCode, KeyModifiers};
+use fresh::config::Config;
+use fresh::view::popup::PopupContent;
+use std::time::Duration;
+
+/// Harness with auto-completion on and a text file of `content` open, with
+/// its words indexed and the cursor at the end
+fn open_text(content: &str, mut config: Config) -> EditorTestHarness {
+    config.editor.auto_complete = true;
+    let mut harness = EditorTestHarness::with_temp_project_and_config(80, 24, config).unwrap();
+    let file = harness.project_dir().unwrap().join("notes.txt");
+    std::fs::write(&file, content).unwrap();
+    harness.open_file(&file).unwrap();
+    harness
+        .send_key(KeyCode::End, KeyModifiers::CONTROL)
+        .unwrap();
+    harness.advance_time(Duration::from_millis(300));
+    for _ in 0..10 {
+        harness.editor_mut().index_words_when_idle();
+    }
+    harness
+}
+
+/// Let `delay` pass and fire the auto-completion timer
+fn pause(harness: &mut EditorTestHarness, delay: Duration) {
+    harness.advance_time(delay);
+    harness.editor_mut().check_auto_completion_timer();
+    harness.render().unwrap();
+}
+
+fn popup_visible(harness: &EditorTestHarness) -> bool {
+    harness.editor().active_state().popups.is_visible()
+}
+
+fn popup_items(harness: &EditorTestHarness) -> Vec<String> {
+    match harness
+        .editor()
+        .active_state()
+        .popups
+        .top()
+        .map(|p| &p.content)
+    {
+        Some(PopupContent::List { items, .. }) => items.iter().map(|i| i.text.clone()).collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn selected_item(harness: &EditorTestHarness) -> Option<String> {
+    harness
+        .editor()
+        .active_state()
+        .popups
+        .top()
+        .and_then(|popup| popup.selected_item())
+        .map(|item| item.text.clone())
+}
+
+/// The popup opens once typing pauses for the delay, and typing more
+/// filters it in place
+#[test]
+fn test_auto_completion_opens_after_typing_pauses() {
+    let mut harness = open_text("compare compute_total\n", Config::default());
+    harness.type_text("com").unwrap();
+
+    pause(&mut harness, Duration::from_millis(100));
+    assert!(!popup_visible(&harness));
+
+    // Typing again restarts the delay
+    harness.type_text("p").unwrap();
+    pause(&mut harness, Duration::from_millis(100));
+    assert!(!popup_visible(&harness));
+    pause(&mut harness, Duration::from_millis(60));
+    let mut items = popup_items(&harness);
+    items.sort();
+    assert_eq!(items, ["compare", "compute_total"]);
+
+    // Move to "compute_total" and keep typing: the popup stays, filtered,
+    // with the same item selected
+    harness.send_key(KeyCode::Down, KeyModifiers::NONE).unwrap();
+    harness.send_key(KeyCode::Up, KeyModifiers::NONE).unwrap();
+    let target = "compute_total".to_string();
+    while selected_item(&harness).as_ref() != Some(&target) {
+        harness.send_key(KeyCode::Down, KeyModifiers::NONE).unwrap();
+    }
+    harness.type_text("u").unwrap();
+    assert_eq!(popup_items(&harness), [target.clone()]);
+    assert_eq!(selected_item(&harness), Some(target));
+
+    // Enter accepts once the list was navigated
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    assert_eq!(
+        harness.get_buffer_content().unwrap(),
+        "compare compute_total\ncompute_total"
+    );
+    assert!(!popup_visible(&harness));
+}
+
+/// Enter and Tab go to the buffer until the list is navigated
+#[test]
+fn test_auto_completion_does_not_take_enter_or_tab() {
+    let mut harness = open_text("compute_total\n", Config::default());
+    harness.type_text("comp").unwrap();
+    pause(&mut harness, Duration::from_millis(150));
+    assert!(popup_visible(&harness));
+
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    assert!(!popup_visible(&harness));
+    assert_eq!(
+        harness.get_buffer_content().unwrap(),
+        "compute_total\ncomp\n"
+    );
+
+    harness.type_text("comp").unwrap();
+    pause(&mut harness, Duration::from_millis(150));
+    assert!(popup_visible(&harness));
+    harness.send_key(KeyCode::Tab, KeyModifiers::NONE).unwrap();
+    assert!(!popup_visible(&harness));
+    assert_eq!(
+        harness.get_buffer_content().unwrap(),
+        "compute_total\ncomp\ncomp    "
+    );
+}
+
+/// Moving the cursor or pressing Escape closes the popup
+#[test]
+fn test_auto_completion_closes_on_cursor_movement() {
+    let mut harness = open_text("compute_total\n", Config::default());
+    harness.type_text("comp").unwrap();
+    pause(&mut harness, Duration::from_millis(150));
+    assert!(popup_visible(&harness));
+
+    // The key moves the cursor as well as closing the popup
+    harness.send_key(KeyCode::Left, KeyModifiers::NONE).unwrap();
+    assert!(!popup_visible(&harness));
+    harness.type_text("X").unwrap();
+    assert_eq!(
+        harness.get_buffer_content().unwrap(),
+        "compute_total\ncomXp"
+    );
+
+    harness.send_key(KeyCode::End, KeyModifiers::NONE).unwrap();
+    harness.type_text(" comp").unwrap();
+    pause(&mut harness, Duration::from_millis(150));
+    assert!(popup_visible(&harness));
+    harness.send_key(KeyCode::Esc, KeyModifiers::NONE).unwrap();
+    assert!(!popup_visible(&harness));
+
+    // Backspacing before the start of the word closes it too
+    harness.type_text(" comp").unwrap();
+    pause(&mut harness, Duration::from_millis(150));
+    assert!(popup_visible(&harness));
+    for _ in 0..4 {
+        harness
+            .send_key(KeyCode::Backspace, KeyModifiers::NONE)
+            .unwrap();
+    }
+    assert!(popup_visible(&harness));
+    harness
+        .send_key(KeyCode::Backspace, KeyModifiers::NONE)
+        .unwrap();
+    assert!(!popup_visible(&harness));
+}
+
+/// Nothing opens when auto-completion is off
+#[test]
+fn test_auto_completion_off() {
+    let mut config = Config::default();
+    config.editor.auto_complete = false;
+    let mut harness = EditorTestHarness::with_temp_project_and_config(80, 24, config).unwrap();
+    let file = harness.project_dir().unwrap().join("notes.txt");
+    std::fs::write(&file, "compute_total\n").unwrap();
+    harness.open_file(&file).unwrap();
+    harness
+        .send_key(KeyCode::End, KeyModifiers::CONTROL)
+        .unwrap();
+    harness.type_text("comp").unwrap();
+    pause(&mut harness, Duration::from_secs(1));
+    assert!(!popup_visible(&harness));
+}
+
+/// Harness with a Rust file open, its language served by the fake LSP
+/// (trigger characters "." and ":")
+fn open_rust(content: &str, mut config: Config) -> (tempfile::TempDir, EditorTestHarness) {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let file = temp_dir.path().join("main.rs");
+    std::fs::write(&file, content).unwrap();
+    config.editor.auto_complete = true;
+    config.lsp.insert(
+        "rust".to_string(),
+        fresh::services::lsp::LspServerConfig {
+            command: FakeLspServer::script_path().to_string_lossy().to_string(),
+            args: vec![],
+            enabled: true,
+            auto_start: true,
+            process_limits: fresh::services::process_limits::ProcessLimits::default(),
+            initialization_options: None,
+            name: None,
+            primary: false,
+        }
+        .into(),
+    );
+    let mut harness =
+        EditorTestHarness::with_config_and_working_dir(80, 24, config, temp_dir.path().into())
+            .unwrap();
+    harness.open_file(&file).unwrap();
+    // The status bar is too narrow to show the whole message
+    harness
+        .wait_until(|h| {
+            h.editor()
+                .get_status_message()
+                .is_some_and(|message| message.contains("LSP (rust) ready"))
+        })
+        .unwrap();
+    harness
+        .send_key(KeyCode::End, KeyModifiers::CONTROL)
+        .unwrap();
+    (temp_dir, harness)
+}
+
+/// Trigger characters of the language server and of the language config
+/// open the popup without waiting for typing to pause
+#[test]
+#[cfg_attr(target_os = "windows", ignore)] // Uses Bash-based fake LSP server
+fn test_auto_completion_trigger_characters() {
+    let _fake_server = FakeLspServer::spawn().unwrap();
+    let mut config = Config::default();
+    config
+        .languages
+        .get_mut("rust")
+        .unwrap()
+        .completion_trigger_characters = vec!["->".to_string()];
+    let (_temp_dir, mut harness) = open_rust("", config);
+
+    harness.type_text("value.").unwrap();
+    harness.wait_until(popup_visible).unwrap();
+    harness.assert_screen_contains("test_function");
+
+    harness.send_key(KeyCode::Esc, KeyModifiers::NONE).unwrap();
+    harness.type_text(" - ").unwrap();
+    harness.render().unwrap();
+    assert!(!popup_visible(&harness));
+    harness.type_text("ptr->").unwrap();
+    harness.wait_until(popup_visible).unwrap();
+    harness.assert_screen_contains("test_variable");
+}
+
+/// Nothing opens while typing in a comment
+#[test]
+#[cfg_attr(target_os = "windows", ignore)] // Uses Bash-based fake LSP server
+fn test_auto_completion_not_in_comments() {
+    let _fake_server = FakeLspServer::spawn().unwrap();
+    let (_temp_dir, mut harness) = open_rust("fn main() {}\n", Config::default());
+
+    harness.type_text("// see value.").unwrap();
+    pause(&mut harness, Duration::from_millis(500));
+    harness.process_async_and_render().unwrap();
+    std::thread::sleep(Duration::from_millis(200));
+    harness.process_async_and_render().unwrap();
+    assert!(!popup_visible(&harness));
+}
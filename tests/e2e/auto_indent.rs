@@ -846,3 +846,145 @@ fn test_indent_after_empty_line_in_function_body() {
         line3_indent, content
     );
 }
+
+/// Enter between a pair of braces puts the closing brace on its own line,
+/// and undoing it is a single step
+#[test]
+fn test_rust_enter_between_braces_splits_block() {
+    let temp_dir = TempDir::new().unwrap();
+    let file_path = temp_dir.path().join("test.rs");
+    std::fs::write(&file_path, "fn main() {}").unwrap();
+
+    let mut harness = harness_with_auto_indent();
+    harness.open_file(&file_path).unwrap();
+
+    // Cursor between the braces
+    harness
+        .send_key(KeyCode::End, KeyModifiers::CONTROL)
+        .unwrap();
+    harness.send_key(KeyCode::Left, KeyModifiers::NONE).unwrap();
+
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    assert_eq!(
+        harness.get_buffer_content().unwrap(),
+        "fn main() {\n    \n}"
+    );
+
+    // The cursor is on the indented line
+    harness.type_text("x").unwrap();
+    assert_eq!(
+        harness.get_buffer_content().unwrap(),
+        "fn main() {\n    x\n}"
+    );
+
+    harness
+        .send_key(KeyCode::Char('z'), KeyModifiers::CONTROL)
+        .unwrap();
+    harness
+        .send_key(KeyCode::Char('z'), KeyModifiers::CONTROL)
+        .unwrap();
+    assert_eq!(harness.get_buffer_content().unwrap(), "fn main() {}");
+}
+
+/// Typing `}` on an indented blank line moves it back to the block's indent
+#[test]
+fn test_rust_close_brace_dedents_with_indent_rules() {
+    let temp_dir = TempDir::new().unwrap();
+    let file_path = temp_dir.path().join("test.rs");
+    std::fs::write(&file_path, "fn main() {\n    if x {\n        foo();").unwrap();
+
+    let mut harness = harness_with_auto_indent();
+    harness.open_file(&file_path).unwrap();
+    harness
+        .send_key(KeyCode::End, KeyModifiers::CONTROL)
+        .unwrap();
+
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    harness.type_text("}").unwrap();
+    assert_eq!(
+        harness.get_buffer_content().unwrap(),
+        "fn main() {\n    if x {\n        foo();\n    }"
+    );
+
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    harness.type_text("}").unwrap();
+    assert_eq!(
+        harness.get_buffer_content().unwrap(),
+        "fn main() {\n    if x {\n        foo();\n    }\n}"
+    );
+}
+
+/// Python: Enter after a colon indents, and typing `else:` dedents the line
+#[test]
+fn test_python_colon_blocks_with_indent_rules() {
+    let temp_dir = TempDir::new().unwrap();
+    let file_path = temp_dir.path().join("test.py");
+    std::fs::write(&file_path, "if x:").unwrap();
+
+    let mut harness = harness_with_auto_indent();
+    harness.open_file(&file_path).unwrap();
+    harness
+        .send_key(KeyCode::End, KeyModifiers::CONTROL)
+        .unwrap();
+
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    harness.type_text("pass").unwrap();
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    assert_eq!(
+        harness.get_buffer_content().unwrap(),
+        "if x:\n    pass\n    "
+    );
+
+    // The line moves left once the colon makes it an `else:` clause
+    harness.type_text("else").unwrap();
+    assert_eq!(
+        harness.get_buffer_content().unwrap(),
+        "if x:\n    pass\n    else"
+    );
+    harness.type_text(":").unwrap();
+    assert_eq!(
+        harness.get_buffer_content().unwrap(),
+        "if x:\n    pass\nelse:"
+    );
+
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    harness.type_text("pass").unwrap();
+    assert_eq!(
+        harness.get_buffer_content().unwrap(),
+        "if x:\n    pass\nelse:\n    pass"
+    );
+}
+
+/// Pasted text is inserted verbatim, without auto-indent
+#[test]
+fn test_python_paste_is_not_reindented() {
+    let temp_dir = TempDir::new().unwrap();
+    let file_path = temp_dir.path().join("test.py");
+    std::fs::write(&file_path, "def f():\n    ").unwrap();
+
+    let mut harness = harness_with_auto_indent();
+    harness.open_file(&file_path).unwrap();
+    harness
+        .send_key(KeyCode::End, KeyModifiers::CONTROL)
+        .unwrap();
+
+    harness
+        .editor_mut()
+        .paste_text("if y:\n  a()\nelse:\n  b()".to_string());
+    assert_eq!(
+        harness.get_buffer_content().unwrap(),
+        "def f():\n    if y:\n  a()\nelse:\n  b()"
+    );
+}
@@ -464,3 +464,211 @@ fn test_auto_revert_with_temp_rename_save() {
         harness.assert_buffer_content(&new_content);
     }
 }
+
+/// Run a command from the command palette
+fn run_command(harness: &mut EditorTestHarness, name: &str) {
+    use crossterm::event::{KeyCode, KeyModifiers};
+    harness
+        .send_key(KeyCode::Char('p'), KeyModifiers::CONTROL)
+        .unwrap();
+    harness.type_text(name).unwrap();
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+}
+
+/// Turning auto-revert on for one buffer reloads it even though the global
+/// setting is off; other buffers keep following the global setting
+#[test]
+fn test_buffer_auto_revert_on_beats_global_off() {
+    let mut config = fresh::config::Config::default();
+    config.files.auto_revert = fresh::config::AutoRevertMode::Off;
+    let mut harness = EditorTestHarness::with_temp_project_and_config(80, 24, config).unwrap();
+    let project_dir = harness.project_dir().unwrap();
+    let other_path = project_dir.join("other.txt");
+    let file_path = project_dir.join("watched.txt");
+    write_and_sync(&other_path, "other v1");
+    write_and_sync(&file_path, "watched v1");
+
+    harness.open_file(&other_path).unwrap();
+    harness.open_file(&file_path).unwrap();
+    run_command(&mut harness, "Toggle Auto-Revert for Buffer");
+    harness.render().unwrap();
+    harness.assert_screen_contains("Auto-revert enabled for");
+
+    harness.sleep(FILE_CHANGE_DELAY);
+    write_and_sync(&other_path, "other v2");
+    write_and_sync(&file_path, "watched v2");
+
+    harness
+        .wait_until(|h| h.get_buffer_content().unwrap() == "watched v2")
+        .expect("Buffer with auto-revert on should be reloaded");
+
+    harness.open_file(&other_path).unwrap();
+    harness.assert_buffer_content("other v1");
+}
+
+/// Turning auto-revert off for one buffer keeps it as it is even though the
+/// global setting is on
+#[test]
+fn test_buffer_auto_revert_off_beats_global_on() {
+    let mut harness = EditorTestHarness::with_temp_project(80, 24).unwrap();
+    let project_dir = harness.project_dir().unwrap();
+    let file_path = project_dir.join("pinned.txt");
+    write_and_sync(&file_path, "pinned v1");
+
+    harness.open_file(&file_path).unwrap();
+    run_command(&mut harness, "Toggle Auto-Revert for Buffer");
+    harness.render().unwrap();
+    harness.assert_screen_contains("Auto-revert disabled for");
+
+    harness.sleep(FILE_CHANGE_DELAY);
+    write_and_sync(&file_path, "pinned v2");
+    for _ in 0..5 {
+        harness.sleep(FILE_CHANGE_DELAY);
+        harness.process_async_and_render().unwrap();
+    }
+
+    harness.assert_buffer_content("pinned v1");
+}
+
+/// Files matching `files.auto_revert_exclude` are never reloaded
+#[test]
+fn test_auto_revert_skips_excluded_files() {
+    let mut config = fresh::config::Config::default();
+    config.files.auto_revert_exclude = vec!["*.log".to_string()];
+    let mut harness = EditorTestHarness::with_temp_project_and_config(80, 24, config).unwrap();
+    let project_dir = harness.project_dir().unwrap();
+    let log_path = project_dir.join("build.log");
+    let file_path = project_dir.join("notes.txt");
+    write_and_sync(&log_path, "log v1");
+    write_and_sync(&file_path, "notes v1");
+
+    harness.open_file(&log_path).unwrap();
+    harness.open_file(&file_path).unwrap();
+
+    harness.sleep(FILE_CHANGE_DELAY);
+    write_and_sync(&log_path, "log v2");
+    write_and_sync(&file_path, "notes v2");
+    harness
+        .wait_until(|h| h.get_buffer_content().unwrap() == "notes v2")
+        .expect("Files that aren't excluded should still be reloaded");
+
+    harness.open_file(&log_path).unwrap();
+    harness.assert_buffer_content("log v1");
+}
+
+/// With `files.auto_revert: "ask"`, a change on disk is only loaded once confirmed
+#[test]
+fn test_auto_revert_ask_reloads_after_confirmation() {
+    use crossterm::event::{KeyCode, KeyModifiers};
+    let mut config = fresh::config::Config::default();
+    config.files.auto_revert = fresh::config::AutoRevertMode::Ask;
+    let mut harness = EditorTestHarness::with_temp_project_and_config(100, 24, config).unwrap();
+    let project_dir = harness.project_dir().unwrap();
+    let file_path = project_dir.join("asked.txt");
+    write_and_sync(&file_path, "asked v1");
+
+    harness.open_file(&file_path).unwrap();
+    harness.sleep(FILE_CHANGE_DELAY);
+    write_and_sync(&file_path, "asked v2");
+
+    harness
+        .wait_for_screen_contains("asked.txt changed on disk. (r)eload")
+        .unwrap();
+    harness.assert_buffer_content("asked v1");
+
+    harness.type_text("r").unwrap();
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    harness.assert_buffer_content("asked v2");
+}
+
+/// A log file with one numbered line per entry
+fn log_lines(range: std::ops::RangeInclusive<usize>) -> String {
+    range.map(|i| format!("Log entry {}\n", i)).collect()
+}
+
+/// Follow-tail keeps the cursor and view at the end as the file grows, and
+/// stops as soon as the user scrolls up
+#[test]
+fn test_follow_tail_pins_view_to_end_until_scrolled_up() {
+    use crossterm::event::{KeyCode, KeyModifiers};
+    let mut harness = EditorTestHarness::with_temp_project(80, 24).unwrap();
+    let project_dir = harness.project_dir().unwrap();
+    let file_path = project_dir.join("app.log");
+    write_and_sync(&file_path, &log_lines(1..=100));
+
+    harness.open_file(&file_path).unwrap();
+    harness.assert_screen_contains("Log entry 1");
+
+    run_command(&mut harness, "Toggle Follow Tail");
+    harness.render().unwrap();
+    harness.assert_screen_contains("[tail]");
+    harness.assert_screen_contains("Log entry 100");
+    assert_eq!(harness.cursor_position(), log_lines(1..=100).len());
+
+    // New entries arrive: the view moves with them
+    harness.sleep(FILE_CHANGE_DELAY);
+    write_and_sync(&file_path, &log_lines(1..=150));
+    harness
+        .wait_until(|h| h.screen_to_string().contains("Log entry 150"))
+        .expect("Follow tail should scroll to the new end");
+    assert_eq!(harness.cursor_position(), log_lines(1..=150).len());
+    harness.assert_screen_not_contains("Log entry 100");
+
+    // Scrolling up leaves follow-tail mode
+    harness
+        .send_key(KeyCode::PageUp, KeyModifiers::NONE)
+        .unwrap();
+    harness.render().unwrap();
+    harness.assert_screen_not_contains("[tail]");
+    let cursor_after_scroll = harness.cursor_position();
+
+    // The buffer is still reloaded, but the view stays where the user put it
+    harness.sleep(FILE_CHANGE_DELAY);
+    write_and_sync(&file_path, &log_lines(1..=200));
+    harness
+        .wait_until(|h| h.get_buffer_content().unwrap() == log_lines(1..=200))
+        .expect("Auto-revert should still reload the buffer");
+    harness.render().unwrap();
+    harness.assert_screen_not_contains("Log entry 200");
+    assert_eq!(harness.cursor_position(), cursor_after_scroll);
+}
+
+/// "Revert Buffer to Saved" loads the file as one edit that undo takes back,
+/// and keeps the cursor on its line when lines are added above it
+#[test]
+fn test_revert_buffer_to_saved_is_undoable() {
+    use crossterm::event::{KeyCode, KeyModifiers};
+    let mut harness = EditorTestHarness::with_temp_project(100, 24).unwrap();
+    let project_dir = harness.project_dir().unwrap();
+    let file_path = project_dir.join("revert.txt");
+    write_and_sync(&file_path, "one\ntwo\nthree\n");
+
+    harness.open_file(&file_path).unwrap();
+    harness.send_key(KeyCode::Down, KeyModifiers::NONE).unwrap();
+    harness.send_key(KeyCode::End, KeyModifiers::NONE).unwrap();
+    harness.type_text("!").unwrap();
+    harness.assert_buffer_content("one\ntwo!\nthree\n");
+
+    write_and_sync(&file_path, "zero\none\ntwo\nthree\n");
+    harness.editor_mut().revert_buffer_to_saved();
+    harness.render().unwrap();
+    harness.assert_screen_contains("(r)evert, (p)review changes");
+    harness.type_text("r").unwrap();
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+
+    harness.assert_buffer_content("zero\none\ntwo\nthree\n");
+    // End of "two", now the third line
+    assert_eq!(harness.cursor_position(), 12);
+    assert!(!harness.editor().active_state().buffer.is_modified());
+
+    harness
+        .send_key(KeyCode::Char('z'), KeyModifiers::CONTROL)
+        .unwrap();
+    harness.assert_buffer_content("one\ntwo!\nthree\n");
+}
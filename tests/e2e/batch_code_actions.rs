@@ -0,0 +1,239 @@
+//! E2E tests for running a code action kind on many files
+//!
+//! The edits of every file go through the pending edits review; confirming
+//! it applies and saves them, except in buffers that already had unsaved
+//! changes.
+
+use crate::common::harness::EditorTestHarness;
+use crossterm::event::{KeyCode, KeyModifiers};
+use fresh::app::BatchScope;
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+const UNUSED: &str = "// unused\nfn main() {}\n";
+const ORGANIZED: &str = "fn main() {}\n";
+
+/// A project with `files`, each starting with a line the fake server's
+/// code action removes
+fn setup(files: &[&str]) -> (TempDir, PathBuf) {
+    let temp_dir = TempDir::new().unwrap();
+    let project = temp_dir.path().canonicalize().unwrap();
+    for file in files {
+        let path = project.join(file);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, UNUSED).unwrap();
+    }
+    (temp_dir, project)
+}
+
+/// Fake language server offering `source.organizeImports`, which deletes
+/// the first line of the file asked about; files named `slow` take three
+/// seconds to answer
+#[cfg(unix)]
+fn write_fake_server(dir: &Path) -> PathBuf {
+    let script = r#"#!/bin/bash
+
+read_message() {
+    local content_length=0
+    while IFS=: read -r key value; do
+        key=$(echo "$key" | tr -d '\r\n')
+        value=$(echo "$value" | tr -d '\r\n ')
+        if [ "$key" = "Content-Length" ]; then
+            content_length=$value
+        fi
+        if [ -z "$key" ]; then
+            break
+        fi
+    done
+
+    if [ $content_length -gt 0 ]; then
+        dd bs=1 count=$content_length 2>/dev/null
+    fi
+}
+
+send_message() {
+    local message="$1"
+    local length=${#message}
+    echo -en "Content-Length: $length\r\n\r\n$message"
+}
+
+while true; do
+    msg=$(read_message)
+
+    if [ -z "$msg" ]; then
+        break
+    fi
+
+    method=$(echo "$msg" | grep -o '"method":"[^"]*"' | cut -d'"' -f4)
+    msg_id=$(echo "$msg" | grep -o '"id":[0-9]*' | cut -d':' -f2)
+
+    case "$method" in
+        "initialize")
+            send_message '{"jsonrpc":"2.0","id":'$msg_id',"result":{"capabilities":{"textDocumentSync":1,"codeActionProvider":{"codeActionKinds":["source.organizeImports"]}}}}'
+            ;;
+        "textDocument/codeAction")
+            uri=$(echo "$msg" | grep -o '"uri":"[^"]*"' | head -1 | cut -d'"' -f4)
+            case "$uri" in
+                *slow*) sleep 3 ;;
+            esac
+            send_message '{"jsonrpc":"2.0","id":'$msg_id',"result":[{"title":"Organize imports","kind":"source.organizeImports","edit":{"changes":{"'$uri'":[{"range":{"start":{"line":0,"character":0},"end":{"line":1,"character":0}},"newText":""}]}}}]}'
+            ;;
+        "shutdown")
+            send_message '{"jsonrpc":"2.0","id":'$msg_id',"result":null}'
+            break
+            ;;
+        *)
+            if [ -n "$msg_id" ]; then
+                send_message '{"jsonrpc":"2.0","id":'$msg_id',"result":null}'
+            fi
+            ;;
+    esac
+done
+"#;
+
+    let script_path = dir.join("fake_code_action_lsp.sh");
+    std::fs::write(&script_path, script).unwrap();
+    use std::os::unix::fs::PermissionsExt;
+    let mut perms = std::fs::metadata(&script_path).unwrap().permissions();
+    perms.set_mode(0o755);
+    std::fs::set_permissions(&script_path, perms).unwrap();
+    script_path
+}
+
+fn harness_with_server(project: &Path, scripts: &Path) -> EditorTestHarness {
+    #[cfg(unix)]
+    let script_path = write_fake_server(scripts);
+    #[cfg(not(unix))]
+    let script_path = scripts.join("unused");
+
+    let mut config = fresh::config::Config::default();
+    config.lsp.insert(
+        "rust".to_string(),
+        fresh::services::lsp::LspServerConfig {
+            command: script_path.to_string_lossy().to_string(),
+            args: vec![],
+            enabled: true,
+            auto_start: true,
+            process_limits: fresh::services::process_limits::ProcessLimits::default(),
+            initialization_options: None,
+            name: None,
+            primary: false,
+        }
+        .into(),
+    );
+    EditorTestHarness::with_config_and_working_dir(100, 30, config, project.to_path_buf()).unwrap()
+}
+
+fn read(project: &Path, file: &str) -> String {
+    std::fs::read_to_string(project.join(file)).unwrap()
+}
+
+/// Picked from the palette with a glob, the action runs only on the
+/// matching files with a server; after review the files are saved, except
+/// an open buffer that already had unsaved changes
+#[test]
+#[cfg_attr(windows, ignore)] // Uses bash script for fake LSP server
+fn test_batch_code_action_on_glob() {
+    let (_temp_dir, project) = setup(&["a.rs", "sub/b.rs", "sub/c.rs", "sub/notes.txt"]);
+    let scripts = TempDir::new().unwrap();
+    let mut harness = harness_with_server(&project, scripts.path());
+
+    harness.open_file(&project.join("sub/b.rs")).unwrap();
+    harness
+        .wait_until(|h| h.editor().is_lsp_server_ready("rust"))
+        .unwrap();
+    harness
+        .send_key(KeyCode::End, KeyModifiers::CONTROL)
+        .unwrap();
+    harness.type_text("// mine\n").unwrap();
+
+    harness
+        .send_key(KeyCode::Char('p'), KeyModifiers::CONTROL)
+        .unwrap();
+    harness.type_text("Apply Code Action to Files").unwrap();
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    harness.wait_for_prompt().unwrap();
+    harness.type_text("organize").unwrap();
+    harness.render().unwrap();
+    harness.assert_screen_contains("source.organizeImports");
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    harness.render().unwrap();
+    harness.assert_screen_contains("Run source.organizeImports on (choice or glob): ");
+    harness.type_text("sub/").unwrap();
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+
+    harness
+        .wait_for_screen_contains("Pending edits: source.organizeImports in 2 files")
+        .unwrap();
+    harness.assert_screen_contains("2/2 edits in 2/2 files");
+    assert_eq!(
+        harness.editor().get_status_message().map(String::as_str),
+        Some("source.organizeImports: 2 changed, 0 unchanged, 0 failed")
+    );
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    harness.render().unwrap();
+    assert!(harness
+        .editor()
+        .get_status_message()
+        .unwrap()
+        .ends_with("; saved 1 files, 1 with unsaved changes left unsaved"));
+
+    assert_eq!(read(&project, "sub/c.rs"), ORGANIZED);
+    assert_eq!(read(&project, "sub/b.rs"), UNUSED);
+    assert_eq!(read(&project, "a.rs"), UNUSED);
+    assert_eq!(read(&project, "sub/notes.txt"), UNUSED);
+    harness.open_file(&project.join("sub/b.rs")).unwrap();
+    harness.assert_buffer_content("fn main() {}\n// mine\n");
+    // c.rs was opened only to apply its edit, and is closed again
+    harness.render().unwrap();
+    harness.assert_screen_not_contains("c.rs");
+}
+
+/// Cancelling stops at the file being waited for; the files answered before
+/// are still reviewed
+#[test]
+#[cfg_attr(windows, ignore)] // Uses bash script for fake LSP server
+fn test_batch_code_action_cancelled() {
+    let (_temp_dir, project) = setup(&["a.rs", "b.rs", "slow.rs", "z.rs"]);
+    let scripts = TempDir::new().unwrap();
+    let mut harness = harness_with_server(&project, scripts.path());
+
+    harness
+        .editor_mut()
+        .run_batch_code_action("source.organizeImports".to_string(), BatchScope::Workspace);
+    assert_eq!(harness.editor().batch_code_action_progress(), Some((0, 4)));
+    harness
+        .wait_until(|h| h.editor().batch_code_action_progress() == Some((2, 4)))
+        .unwrap();
+    harness.assert_screen_contains("2/4 files");
+
+    let progress_id = harness.editor().progress().entries()[0].id;
+    assert!(harness.editor_mut().cancel_progress(progress_id));
+    harness
+        .wait_until(|h| !h.editor().is_running_batch_code_action())
+        .unwrap();
+    assert_eq!(
+        harness.editor().get_status_message().map(String::as_str),
+        Some(
+            "source.organizeImports: 2 changed, 0 unchanged, 0 failed; \
+             stopped at slow.rs (2/4 files)"
+        )
+    );
+    harness.assert_screen_contains("2/2 edits in 2/2 files");
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+
+    assert_eq!(read(&project, "a.rs"), ORGANIZED);
+    assert_eq!(read(&project, "b.rs"), ORGANIZED);
+    assert_eq!(read(&project, "slow.rs"), UNUSED);
+    assert_eq!(read(&project, "z.rs"), UNUSED);
+}
@@ -0,0 +1,163 @@
+//! E2E tests for compacting buffer memory and the buffer memory report
+
+use crate::common::fixtures::TestFixture;
+use crate::common::harness::EditorTestHarness;
+use crossterm::event::{KeyCode, KeyModifiers};
+use fresh::config::Config;
+use fresh::services::plugins::api::PluginCommand;
+use std::time::Duration;
+
+fn execute_action(harness: &mut EditorTestHarness, name: &str) {
+    harness
+        .editor_mut()
+        .handle_plugin_command(PluginCommand::ExecuteAction {
+            action_name: name.to_string(),
+        })
+        .unwrap();
+    harness.render().unwrap();
+}
+
+/// Run "Compact Buffer" (or "Compact All Buffers") answering `answer`
+fn compact(harness: &mut EditorTestHarness, action: &str, answer: &str) {
+    execute_action(harness, action);
+    harness.type_text(answer).unwrap();
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+}
+
+fn notified(harness: &EditorTestHarness, text: &str) -> bool {
+    harness
+        .editor()
+        .notifications()
+        .history()
+        .any(|notification| notification.message.contains(text))
+}
+
+#[test]
+fn test_compact_buffer_keeps_last_undo_steps() {
+    let fixture = TestFixture::new("notes.txt", "start\n").unwrap();
+    let mut harness = EditorTestHarness::new(100, 24).unwrap();
+    harness.open_file(&fixture.path).unwrap();
+    harness.type_text("abcdef").unwrap();
+    let cursor = harness.cursor_position();
+
+    compact(&mut harness, "compact_buffer", "2");
+    assert_eq!(harness.get_buffer_content().unwrap(), "abcdefstart\n");
+    assert_eq!(harness.cursor_position(), cursor);
+    assert!(notified(&harness, "Compacted 1 buffer(s)"));
+
+    // The two newest steps still undo and redo, the older ones are gone
+    execute_action(&mut harness, "undo");
+    execute_action(&mut harness, "undo");
+    assert_eq!(harness.get_buffer_content().unwrap(), "abcdstart\n");
+    execute_action(&mut harness, "undo");
+    assert_eq!(harness.get_buffer_content().unwrap(), "abcdstart\n");
+    execute_action(&mut harness, "redo");
+    assert_eq!(harness.get_buffer_content().unwrap(), "abcdestart\n");
+    assert!(harness.editor().active_state().buffer.is_modified());
+}
+
+#[test]
+fn test_compact_saved_buffer_discards_history() {
+    let fixture = TestFixture::new("notes.txt", "start\nend\n").unwrap();
+    let mut harness = EditorTestHarness::new(100, 24).unwrap();
+    harness.open_file(&fixture.path).unwrap();
+    harness.type_text("abc").unwrap();
+    harness
+        .send_key(KeyCode::Char('s'), KeyModifiers::CONTROL)
+        .unwrap();
+    harness
+        .wait_until(|h| !h.editor().active_state().buffer.is_modified())
+        .unwrap();
+    harness.send_key(KeyCode::Down, KeyModifiers::NONE).unwrap();
+    harness.send_key(KeyCode::Home, KeyModifiers::NONE).unwrap();
+    let cursor = harness.cursor_position();
+    let buffer_id = harness.editor().active_buffer();
+    assert!(harness.editor().buffer_memory(buffer_id).unwrap().undo > 0);
+
+    compact(&mut harness, "compact_buffer", "d");
+    assert_eq!(harness.get_buffer_content().unwrap(), "abcstart\nend\n");
+    assert_eq!(harness.cursor_position(), cursor);
+    assert_eq!(harness.editor().buffer_memory(buffer_id).unwrap().undo, 0);
+    assert!(!harness.editor().active_state().buffer.is_modified());
+
+    // Nothing is left to undo, and editing goes on as before
+    execute_action(&mut harness, "undo");
+    assert_eq!(harness.get_buffer_content().unwrap(), "abcstart\nend\n");
+    harness.type_text("x").unwrap();
+    assert_eq!(harness.get_buffer_content().unwrap(), "abcstart\nxend\n");
+    execute_action(&mut harness, "undo");
+    assert_eq!(harness.get_buffer_content().unwrap(), "abcstart\nend\n");
+    assert!(!harness.editor().active_state().buffer.is_modified());
+}
+
+#[test]
+fn test_compact_all_buffers_and_memory_report() {
+    let first = TestFixture::new("first.txt", "one\n").unwrap();
+    let second = TestFixture::new("second.txt", "two\n").unwrap();
+    let mut harness = EditorTestHarness::new(100, 24).unwrap();
+    harness.open_file(&first.path).unwrap();
+    let first_id = harness.editor().active_buffer();
+    harness.type_text("1").unwrap();
+    harness.open_file(&second.path).unwrap();
+    let second_id = harness.editor().active_buffer();
+    harness.type_text("2").unwrap();
+
+    execute_action(&mut harness, "buffer_memory_report");
+    harness.assert_screen_contains("Estimated memory per buffer");
+    harness.assert_screen_contains("first.txt");
+    harness.assert_screen_contains("second.txt");
+    harness.assert_screen_contains("(all buffers)");
+
+    // Anything but "d" or a number cancels
+    compact(&mut harness, "compact_all_buffers", "no");
+    assert!(!notified(&harness, "Compacted"));
+
+    compact(&mut harness, "compact_all_buffers", "d");
+    assert!(notified(&harness, "Compacted"));
+    for buffer_id in [first_id, second_id] {
+        assert_eq!(harness.editor().buffer_memory(buffer_id).unwrap().undo, 0);
+    }
+}
+
+/// Only buffers left alone for `auto_compact_idle_minutes` lose undo steps
+#[test]
+fn test_auto_compact_only_touches_idle_buffers() {
+    let mut config = Config::default();
+    config.performance.auto_compact = true;
+    config.performance.auto_compact_idle_minutes = 30;
+    config.performance.auto_compact_undo_depth = 1;
+    let idle = TestFixture::new("idle.txt", "a\n").unwrap();
+    let busy = TestFixture::new("busy.txt", "b\n").unwrap();
+    let mut harness = EditorTestHarness::with_config(100, 24, config).unwrap();
+
+    harness.open_file(&idle.path).unwrap();
+    let idle_id = harness.editor().active_buffer();
+    harness.type_text("123").unwrap();
+    // Let the once-a-minute check see the typing
+    harness.advance_time(Duration::from_secs(60));
+    harness.process_async_and_render().unwrap();
+
+    harness.open_file(&busy.path).unwrap();
+    harness.type_text("456").unwrap();
+    harness.advance_time(Duration::from_secs(20 * 60));
+    harness.process_async_and_render().unwrap();
+    harness.type_text("7").unwrap();
+    harness.advance_time(Duration::from_secs(15 * 60));
+    harness.process_async_and_render().unwrap();
+
+    // Typed into 15 minutes ago: all of its history is still there
+    for _ in 0..5 {
+        execute_action(&mut harness, "undo");
+    }
+    assert_eq!(harness.get_buffer_content().unwrap(), "b\n");
+
+    // Untouched for 35 minutes: only the last step is left
+    harness.editor_mut().switch_buffer(idle_id);
+    harness.render().unwrap();
+    assert_eq!(harness.get_buffer_content().unwrap(), "123a\n");
+    execute_action(&mut harness, "undo");
+    execute_action(&mut harness, "undo");
+    assert_eq!(harness.get_buffer_content().unwrap(), "12a\n");
+}
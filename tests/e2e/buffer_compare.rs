@@ -0,0 +1,207 @@
+//! E2E tests for "Compare Buffer With…" and "Compare With Clipboard"
+
+use crate::common::harness::EditorTestHarness;
+use crossterm::event::{KeyCode, KeyModifiers};
+use fresh::model::event::BufferId;
+use fresh::services::plugins::api::PluginCommand;
+use std::time::Duration;
+
+fn execute_action(harness: &mut EditorTestHarness, name: &str) {
+    harness
+        .editor_mut()
+        .handle_plugin_command(PluginCommand::ExecuteAction {
+            action_name: name.to_string(),
+        })
+        .unwrap();
+    harness.render().unwrap();
+}
+
+/// A new untitled buffer holding `lines`, each followed by a newline
+fn buffer_with_lines(harness: &mut EditorTestHarness, lines: &[&str]) -> BufferId {
+    harness.new_buffer().unwrap();
+    for line in lines {
+        harness.type_text(line).unwrap();
+        harness
+            .send_key(KeyCode::Enter, KeyModifiers::NONE)
+            .unwrap();
+    }
+    harness.editor().active_buffer()
+}
+
+/// Compare "one two three four" on the left with "one TWO three four
+/// five" on the right
+fn compare_two_buffers(harness: &mut EditorTestHarness) -> (BufferId, BufferId) {
+    let left = buffer_with_lines(harness, &["one", "two", "three", "four"]);
+    let right = buffer_with_lines(harness, &["one", "TWO", "three", "four", "five"]);
+    harness.editor_mut().compare_buffers(left, right);
+    harness.render().unwrap();
+    (left, right)
+}
+
+/// Copying hunks edits the buffer on the receiving side, the hunks are
+/// recomputed right away, and one undo brings a copied hunk back
+#[test]
+fn test_copy_hunks_between_compared_buffers() {
+    let mut harness = EditorTestHarness::new(100, 24).unwrap();
+    let (left, right) = compare_two_buffers(&mut harness);
+
+    assert_eq!(harness.editor().get_split_count(), 2);
+    assert_eq!(harness.editor().active_buffer(), left);
+    assert_eq!(
+        harness.editor().compare_hunks(),
+        Some(vec![(1..2, 1..2), (4..4, 4..5)])
+    );
+    harness.assert_screen_contains("TWO");
+
+    // From the end of the left buffer back to the changed line
+    execute_action(&mut harness, "compare_previous_hunk");
+    execute_action(&mut harness, "compare_copy_hunk_to_left");
+    assert_eq!(
+        harness.editor().get_buffer_content(left).unwrap(),
+        "one\nTWO\nthree\nfour\n"
+    );
+    assert_eq!(harness.editor().compare_hunks(), Some(vec![(4..4, 4..5)]));
+
+    // Dropping the right buffer's extra line from the left side
+    execute_action(&mut harness, "compare_next_hunk");
+    execute_action(&mut harness, "compare_copy_hunk_to_right");
+    assert_eq!(
+        harness.editor().get_buffer_content(right).unwrap(),
+        "one\nTWO\nthree\nfour\n"
+    );
+    assert_eq!(harness.editor().compare_hunks(), Some(vec![]));
+
+    // Undoing the copy in the left buffer is picked up after a pause
+    harness
+        .send_key(KeyCode::Char('z'), KeyModifiers::CONTROL)
+        .unwrap();
+    assert_eq!(
+        harness.editor().get_buffer_content(left).unwrap(),
+        "one\ntwo\nthree\nfour\n"
+    );
+    harness.process_async_and_render().unwrap();
+    harness.advance_time(Duration::from_millis(300));
+    harness.process_async_and_render().unwrap();
+    assert_eq!(harness.editor().compare_hunks(), Some(vec![(1..2, 1..2)]));
+}
+
+/// Typing in a compared buffer updates the hunks only once the typing stops
+#[test]
+fn test_compare_recomputes_after_edits_settle() {
+    let mut harness = EditorTestHarness::new(100, 24).unwrap();
+    compare_two_buffers(&mut harness);
+
+    harness
+        .send_key(KeyCode::Home, KeyModifiers::CONTROL)
+        .unwrap();
+    harness.type_text("x").unwrap();
+    harness.process_async_and_render().unwrap();
+    assert_eq!(
+        harness.editor().compare_hunks(),
+        Some(vec![(1..2, 1..2), (4..4, 4..5)])
+    );
+
+    harness.advance_time(Duration::from_millis(300));
+    harness.process_async_and_render().unwrap();
+    assert_eq!(
+        harness.editor().compare_hunks(),
+        Some(vec![(0..2, 0..2), (4..4, 4..5)])
+    );
+}
+
+/// Closing a compared buffer removes the comparison's highlighting
+#[test]
+fn test_closing_compared_buffer_ends_comparison() {
+    let mut harness = EditorTestHarness::new(100, 24).unwrap();
+    let (_, right) = compare_two_buffers(&mut harness);
+    assert_eq!(harness.editor().active_state().overlays.all().len(), 1);
+
+    harness.editor_mut().force_close_buffer(right).unwrap();
+    harness.render().unwrap();
+
+    assert_eq!(harness.editor().compare_hunks(), None);
+    assert!(harness.editor().active_state().overlays.all().is_empty());
+    harness.process_async_and_render().unwrap();
+}
+
+/// The selection is compared with the clipboard's text; hunks can be copied
+/// into the buffer but not into the read-only clipboard side
+#[test]
+fn test_compare_selection_with_clipboard() {
+    let mut harness = EditorTestHarness::new(100, 24).unwrap();
+    let buffer = buffer_with_lines(&mut harness, &["one", "two", "three", "four"]);
+    harness
+        .editor_mut()
+        .set_clipboard_for_test("TWO\nthree\n".to_string());
+    // Select "two" and "three"
+    harness
+        .send_key(KeyCode::Home, KeyModifiers::CONTROL)
+        .unwrap();
+    harness.send_key(KeyCode::Down, KeyModifiers::NONE).unwrap();
+    harness
+        .send_key(KeyCode::Down, KeyModifiers::SHIFT)
+        .unwrap();
+    harness
+        .send_key(KeyCode::Down, KeyModifiers::SHIFT)
+        .unwrap();
+
+    execute_action(&mut harness, "compare_with_clipboard");
+    assert_eq!(harness.editor().get_split_count(), 2);
+    assert_eq!(harness.editor().active_buffer(), buffer);
+    assert_eq!(harness.editor().compare_hunks(), Some(vec![(1..2, 0..1)]));
+    harness.assert_screen_contains("*Clipboard*");
+    harness.assert_screen_contains("TWO");
+
+    execute_action(&mut harness, "compare_previous_hunk");
+    execute_action(&mut harness, "compare_copy_hunk_to_right");
+    assert_eq!(harness.editor().compare_hunks(), Some(vec![(1..2, 0..1)]));
+
+    execute_action(&mut harness, "compare_copy_hunk_to_left");
+    assert_eq!(
+        harness.editor().get_buffer_content(buffer).unwrap(),
+        "one\nTWO\nthree\nfour\n"
+    );
+    assert_eq!(harness.editor().compare_hunks(), Some(vec![]));
+}
+
+/// The clipboard's scratch buffer is discarded when the comparison ends
+#[test]
+fn test_clipboard_side_is_closed_with_comparison() {
+    let mut harness = EditorTestHarness::new(100, 24).unwrap();
+    buffer_with_lines(&mut harness, &["one", "two"]);
+    harness
+        .editor_mut()
+        .set_clipboard_for_test("one\n2\n".to_string());
+
+    execute_action(&mut harness, "compare_with_clipboard");
+    assert_eq!(harness.editor().compare_hunks(), Some(vec![(1..2, 1..2)]));
+    execute_action(&mut harness, "next_split");
+    let scratch = harness.editor().active_buffer();
+    assert_eq!(
+        harness.editor().get_buffer_content(scratch).unwrap(),
+        "one\n2\n"
+    );
+
+    execute_action(&mut harness, "close_split");
+    harness.process_async_and_render().unwrap();
+    assert_eq!(harness.editor().compare_hunks(), None);
+    assert_eq!(harness.editor().get_buffer_content(scratch), None);
+}
+
+/// With nothing on the clipboard, a notification says so
+#[test]
+fn test_compare_with_empty_clipboard() {
+    let mut harness = EditorTestHarness::new(100, 24).unwrap();
+    buffer_with_lines(&mut harness, &["one"]);
+    harness.editor_mut().set_clipboard_for_test(String::new());
+
+    execute_action(&mut harness, "compare_with_clipboard");
+    assert_eq!(harness.editor().get_split_count(), 1);
+    assert_eq!(harness.editor().compare_hunks(), None);
+    let warned = harness
+        .editor()
+        .notifications()
+        .history()
+        .any(|notification| notification.message.contains("clipboard is empty"));
+    assert!(warned);
+}
@@ -0,0 +1,176 @@
+//! E2E tests for pinned buffers
+
+use crate::common::harness::EditorTestHarness;
+use crossterm::event::{KeyCode, KeyModifiers};
+use fresh::config::Config;
+use fresh::config_io::DirectoryContext;
+use fresh::model::event::BufferId;
+use fresh::services::plugins::api::PluginCommand;
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+fn execute_action(harness: &mut EditorTestHarness, name: &str) {
+    harness
+        .editor_mut()
+        .handle_plugin_command(PluginCommand::ExecuteAction {
+            action_name: name.to_string(),
+        })
+        .unwrap();
+    harness.render().unwrap();
+}
+
+/// Write and open `names` in `dir`, returning their buffers in order
+fn open_files(harness: &mut EditorTestHarness, dir: &Path, names: &[&str]) -> Vec<BufferId> {
+    names
+        .iter()
+        .map(|name| {
+            let path = dir.join(name);
+            std::fs::write(&path, format!("content of {}\n", name)).unwrap();
+            harness.open_file(&path).unwrap();
+            harness.editor().active_buffer()
+        })
+        .collect()
+}
+
+fn is_open(harness: &EditorTestHarness, buffer_id: BufferId) -> bool {
+    harness.editor().get_buffer_content(buffer_id).is_some()
+}
+
+/// Close Others leaves the two pinned tabs open and says it kept them
+#[test]
+fn test_close_others_keeps_pinned_buffers() {
+    let temp_dir = TempDir::new().unwrap();
+    let mut harness = EditorTestHarness::new(100, 24).unwrap();
+    let ids = open_files(
+        &mut harness,
+        temp_dir.path(),
+        &["a.txt", "b.txt", "c.txt", "d.txt"],
+    );
+
+    for &id in &[ids[0], ids[2]] {
+        harness.editor_mut().switch_buffer(id);
+        execute_action(&mut harness, "toggle_pin_buffer");
+        assert!(harness.editor().is_buffer_pinned(id));
+    }
+    harness.assert_screen_contains(fresh::view::glyphs::glyphs().pinned);
+
+    let split_id = harness.editor().get_active_split();
+    harness
+        .editor_mut()
+        .close_other_tabs_in_split(ids[3], split_id);
+    harness.render().unwrap();
+
+    assert!(is_open(&harness, ids[0]));
+    assert!(!is_open(&harness, ids[1]));
+    assert!(is_open(&harness, ids[2]));
+    assert!(is_open(&harness, ids[3]));
+    harness.assert_screen_contains("kept 2 pinned");
+
+    // Unpinning changes nothing else; the next bulk close takes it
+    harness.editor_mut().switch_buffer(ids[0]);
+    execute_action(&mut harness, "toggle_pin_buffer");
+    assert!(!harness.editor().is_buffer_pinned(ids[0]));
+    assert!(is_open(&harness, ids[0]));
+    harness
+        .editor_mut()
+        .close_other_tabs_in_split(ids[3], split_id);
+    assert!(!is_open(&harness, ids[0]));
+    assert!(is_open(&harness, ids[2]));
+}
+
+/// Close Saved Buffers skips pinned buffers too
+#[test]
+fn test_close_saved_buffers_keeps_pinned_buffers() {
+    let temp_dir = TempDir::new().unwrap();
+    let mut harness = EditorTestHarness::new(100, 24).unwrap();
+    let ids = open_files(&mut harness, temp_dir.path(), &["a.txt", "b.txt"]);
+    harness
+        .editor_mut()
+        .handle_plugin_command(PluginCommand::SetBufferPinned {
+            buffer_id: ids[0],
+            pinned: true,
+        })
+        .unwrap();
+
+    execute_action(&mut harness, "close_saved_buffers");
+    assert!(is_open(&harness, ids[0]));
+    assert!(!is_open(&harness, ids[1]));
+    harness.assert_screen_contains("kept 1 pinned");
+}
+
+/// The tab switcher lists pinned buffers first, each group in tab order
+#[test]
+fn test_switcher_lists_pinned_first() {
+    let temp_dir = TempDir::new().unwrap();
+    let mut harness = EditorTestHarness::new(120, 30).unwrap();
+    let scratch = harness.editor().active_buffer();
+    let ids = open_files(
+        &mut harness,
+        temp_dir.path(),
+        &["a.txt", "b.txt", "c.txt", "d.txt"],
+    );
+    // Leave only the four files in the tab bar
+    if !ids.contains(&scratch) {
+        harness.editor_mut().close_buffer(scratch).unwrap();
+    }
+    for &id in &[ids[3], ids[1]] {
+        harness.editor_mut().switch_buffer(id);
+        execute_action(&mut harness, "toggle_pin_buffer");
+    }
+
+    assert_eq!(harness.editor().active_buffer(), ids[1]);
+
+    // The switcher starts on the active tab; stepping down one entry at a
+    // time visits b, d, a, c
+    for expected in [ids[3], ids[0], ids[2]] {
+        execute_action(&mut harness, "switch_to_tab_by_name");
+        harness.send_key(KeyCode::Down, KeyModifiers::NONE).unwrap();
+        harness
+            .send_key(KeyCode::Enter, KeyModifiers::NONE)
+            .unwrap();
+        assert_eq!(harness.editor().active_buffer(), expected);
+    }
+}
+
+/// Pins of file buffers are saved in the session and restored with it
+#[test]
+fn test_pins_survive_session_round_trip() {
+    let temp_dir = TempDir::new().unwrap();
+    let dir_context = DirectoryContext::for_testing(&temp_dir.path().join("dirs"));
+    let project_dir = temp_dir.path().join("project");
+    std::fs::create_dir(&project_dir).unwrap();
+
+    {
+        let mut harness = EditorTestHarness::with_shared_dir_context(
+            80,
+            24,
+            Config::default(),
+            project_dir.clone(),
+            dir_context.clone(),
+        )
+        .unwrap();
+        let ids = open_files(&mut harness, &project_dir, &["a.txt", "b.txt"]);
+        harness.editor_mut().switch_buffer(ids[0]);
+        execute_action(&mut harness, "toggle_pin_buffer");
+
+        let session = harness.editor().capture_session();
+        assert_eq!(session.pinned_files, vec![PathBuf::from("a.txt")]);
+        harness.editor_mut().save_session().unwrap();
+    }
+
+    let mut harness = EditorTestHarness::with_shared_dir_context(
+        80,
+        24,
+        Config::default(),
+        project_dir.clone(),
+        dir_context.clone(),
+    )
+    .unwrap();
+    assert!(harness.editor_mut().try_restore_session().unwrap());
+    harness.open_file(&project_dir.join("a.txt")).unwrap();
+    let a = harness.editor().active_buffer();
+    harness.open_file(&project_dir.join("b.txt")).unwrap();
+    let b = harness.editor().active_buffer();
+    assert!(harness.editor().is_buffer_pinned(a));
+    assert!(!harness.editor().is_buffer_pinned(b));
+}
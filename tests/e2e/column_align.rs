@@ -0,0 +1,129 @@
+//! E2E tests for "Paste as Column" and "Align Selections by Character"
+
+use crate::common::harness::EditorTestHarness;
+use crossterm::event::{KeyCode, KeyModifiers};
+use fresh::services::plugins::api::PluginCommand;
+
+fn execute_action(harness: &mut EditorTestHarness, name: &str) {
+    harness
+        .editor_mut()
+        .handle_plugin_command(PluginCommand::ExecuteAction {
+            action_name: name.to_string(),
+        })
+        .unwrap();
+    harness.render().unwrap();
+}
+
+fn align_on(harness: &mut EditorTestHarness, delimiter: &str) {
+    execute_action(harness, "align_selections");
+    harness.assert_screen_contains("Align on:");
+    harness.type_text(delimiter).unwrap();
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+}
+
+/// The fields of a struct literal line up on ':', counting the CJK field
+/// name as two cells per character, and one undo brings the original back
+#[test]
+fn test_align_struct_literal_and_undo() {
+    let mut harness = EditorTestHarness::new(80, 24).unwrap();
+    let original = "let p = Point {\n    x: 1,\n    long_name: 2,\n    名前: 3,\n};\n";
+    let _fixture = harness.load_buffer_from_text(original).unwrap();
+
+    // Select the three field lines
+    harness.send_key(KeyCode::Down, KeyModifiers::NONE).unwrap();
+    for _ in 0..3 {
+        harness
+            .send_key(KeyCode::Down, KeyModifiers::SHIFT)
+            .unwrap();
+    }
+
+    align_on(&mut harness, ":");
+    assert_eq!(
+        harness.get_buffer_content().unwrap(),
+        "let p = Point {\n    x        : 1,\n    long_name: 2,\n    名前     : 3,\n};\n"
+    );
+
+    harness
+        .send_key(KeyCode::Char('z'), KeyModifiers::CONTROL)
+        .unwrap();
+    assert_eq!(harness.get_buffer_content().unwrap(), original);
+}
+
+/// Lines without the delimiter are left alone, and a message says so
+#[test]
+fn test_align_skips_lines_without_delimiter() {
+    let mut harness = EditorTestHarness::new(80, 24).unwrap();
+    let _fixture = harness
+        .load_buffer_from_text("a => 1\nnothing here\nabc => 2\n")
+        .unwrap();
+    for _ in 0..2 {
+        harness
+            .send_key(KeyCode::Down, KeyModifiers::SHIFT)
+            .unwrap();
+    }
+    harness.send_key(KeyCode::End, KeyModifiers::SHIFT).unwrap();
+
+    align_on(&mut harness, "=>");
+    assert_eq!(
+        harness.get_buffer_content().unwrap(),
+        "a   => 1\nnothing here\nabc => 2\n"
+    );
+    assert_eq!(
+        harness.editor().get_status_message().unwrap(),
+        "Aligned; 1 line(s) without '=>' left as they were"
+    );
+
+    // Nothing to do when no line has it
+    align_on(&mut harness, ";");
+    assert_eq!(
+        harness.get_buffer_content().unwrap(),
+        "a   => 1\nnothing here\nabc => 2\n"
+    );
+    assert_eq!(
+        harness.editor().get_status_message().unwrap(),
+        "No selected line contains ';'"
+    );
+}
+
+/// With one cursor, each line goes down the same column, padding short
+/// lines and adding lines past the end of the buffer
+#[test]
+fn test_paste_as_column_single_cursor() {
+    let mut harness = EditorTestHarness::new(80, 24).unwrap();
+    let original = "abcdef\nab\n";
+    let _fixture = harness.load_buffer_from_text(original).unwrap();
+    for _ in 0..3 {
+        harness
+            .send_key(KeyCode::Right, KeyModifiers::NONE)
+            .unwrap();
+    }
+    harness
+        .editor_mut()
+        .set_clipboard_for_test("1\n2\n3\n".to_string());
+
+    execute_action(&mut harness, "paste_as_column");
+    assert_eq!(harness.get_buffer_content().unwrap(), "abc1def\nab 2\n   3");
+
+    harness
+        .send_key(KeyCode::Char('z'), KeyModifiers::CONTROL)
+        .unwrap();
+    assert_eq!(harness.get_buffer_content().unwrap(), original);
+}
+
+/// With several cursors, line k goes at the k-th cursor
+#[test]
+fn test_paste_as_column_one_line_per_cursor() {
+    let mut harness = EditorTestHarness::new(80, 24).unwrap();
+    let _fixture = harness.load_buffer_from_text("a\nb\nc\n").unwrap();
+    execute_action(&mut harness, "add_cursor_below");
+    execute_action(&mut harness, "add_cursor_below");
+    assert_eq!(harness.editor().active_state().cursors.count(), 3);
+    harness
+        .editor_mut()
+        .set_clipboard_for_test("X\nY\n".to_string());
+
+    execute_action(&mut harness, "paste_as_column");
+    assert_eq!(harness.get_buffer_content().unwrap(), "Xa\nYb\nc\n");
+}
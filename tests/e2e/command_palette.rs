@@ -19,6 +19,10 @@ fn test_command_palette_trigger() {
     // Check that suggestions are visible (commands sorted alphabetically, so Add Cursor commands appear first)
     harness.assert_screen_contains("Add Cursor Above");
     harness.assert_screen_contains("Add Cursor Below");
+
+    // More commands sort before this one than fit
+    harness.type_text("close").unwrap();
+    harness.render().unwrap();
     harness.assert_screen_contains("Close Buffer");
 }
 
@@ -502,8 +506,14 @@ fn test_command_palette_from_file_explorer() {
     // Should show the command palette
     harness.assert_screen_contains("Command:");
 
-    // Should show commands
-    harness.assert_screen_contains("Open File");
+    // Should show commands; more of them sort before this one than fit
+    harness.type_text("focus").unwrap();
+    harness.assert_screen_contains("Focus Editor");
+    for _ in 0.."focus".len() {
+        harness
+            .send_key(KeyCode::Backspace, KeyModifiers::NONE)
+            .unwrap();
+    }
 
     // Should be able to execute a command
     harness.type_text("toggle hidden").unwrap();
@@ -566,8 +576,9 @@ fn test_command_palette_down_no_wraparound() {
         .send_key(KeyCode::Char('p'), KeyModifiers::CONTROL)
         .unwrap();
 
-    // Filter to get only two commands
-    harness.type_text("save f").unwrap();
+    // Filter to get only two commands ("save f" also matches "Close Saved
+    // Buffers")
+    harness.type_text("save file").unwrap();
     harness.render().unwrap();
 
     // Should match "Save File" and "Save File As"
@@ -703,8 +714,12 @@ fn test_command_palette_shows_shortcuts() {
     // Add Cursor Below should show Ctrl+Alt+↓
     harness.assert_screen_contains("Add Cursor Below");
 
-    // Copy should show Ctrl+C (or ⌘+C on macOS)
+    // Copy should show Ctrl+C (or ⌘+C on macOS); more commands sort before
+    // it than fit
+    harness.type_text("copy").unwrap();
+    harness.render().unwrap();
     harness.assert_screen_contains("Copy");
+    let screen = harness.screen_to_string();
     assert!(
         screen.contains("Ctrl+C") || screen.contains("⌘+C"),
         "Should show shortcut for Copy"
@@ -906,9 +921,10 @@ fn test_command_palette_description_fuzzy_matching() {
             .unwrap();
     }
 
-    // Search for "clipboard" which appears in Copy/Paste descriptions
-    // but not in the command names
-    harness.type_text("clipboard").unwrap();
+    // Search for "to clipboard" which appears in Copy/Paste descriptions
+    // but not in the command names ("clipboard" alone matches "Retry System
+    // Clipboard")
+    harness.type_text("to clipboard").unwrap();
     harness.render().unwrap();
 
     // Should find Copy or Paste commands
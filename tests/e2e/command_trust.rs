@@ -0,0 +1,155 @@
+//! E2E tests for the trust popup guarding commands named by project config
+
+use crate::common::harness::EditorTestHarness;
+use crossterm::event::{KeyCode, KeyModifiers};
+use fresh::config::Config;
+use fresh::config_io::DirectoryContext;
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+const UNSORTED: &str = "cherry\napple\nbanana\n";
+const SORTED: &str = "apple\nbanana\ncherry\n";
+
+/// A project whose `.fresh/config.json` formats text files with `sort`
+fn sort_project(temp_dir: &TempDir) -> (DirectoryContext, PathBuf, PathBuf) {
+    let dir_context = DirectoryContext::for_testing(&temp_dir.path().join("dirs"));
+    let project_dir = temp_dir.path().join("project");
+    std::fs::create_dir_all(project_dir.join(".fresh")).unwrap();
+    std::fs::write(
+        project_dir.join(".fresh").join("config.json"),
+        r#"{
+            "languages": {
+                "plaintext": {
+                    "extensions": ["txt"],
+                    "grammar": "plaintext",
+                    "formatter": {"command": "sort", "stdin": true},
+                    "format_on_save": true
+                }
+            }
+        }"#,
+    )
+    .unwrap();
+    let file = project_dir.join("fruit.txt");
+    std::fs::write(&file, UNSORTED).unwrap();
+    (dir_context, project_dir, file)
+}
+
+/// Start an editor on the project, as after a restart, with the file open
+fn start(dir_context: &DirectoryContext, project_dir: &Path, file: &Path) -> EditorTestHarness {
+    let config = Config::load_with_layers(dir_context, project_dir);
+    let mut harness = EditorTestHarness::with_shared_dir_context(
+        100,
+        24,
+        config,
+        project_dir.to_path_buf(),
+        dir_context.clone(),
+    )
+    .unwrap();
+    harness.open_file(file).unwrap();
+    harness
+}
+
+fn save(harness: &mut EditorTestHarness) {
+    harness
+        .send_key(KeyCode::Char('s'), KeyModifiers::CONTROL)
+        .unwrap();
+    harness.render().unwrap();
+}
+
+/// Save, see the formatter held back, and pick the `choice`th answer
+fn save_and_decide(harness: &mut EditorTestHarness, choice: usize) {
+    save(harness);
+    harness.assert_buffer_content(UNSORTED);
+    harness.assert_screen_contains("Waiting for approval to run sort");
+
+    assert!(harness.editor_mut().check_command_trust_requests());
+    harness.render().unwrap();
+    harness.assert_screen_contains("Run project command?");
+    harness.assert_screen_contains("wants to run: sort");
+    harness.assert_screen_contains("config.json");
+    for _ in 0..choice {
+        harness.send_key(KeyCode::Down, KeyModifiers::NONE).unwrap();
+    }
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    harness.render().unwrap();
+    assert!(!harness.editor_mut().check_command_trust_requests());
+}
+
+#[test]
+#[cfg_attr(not(unix), ignore = "On-save actions require Unix-like environment")]
+fn test_allow_once_lasts_until_restart() {
+    let temp_dir = TempDir::new().unwrap();
+    let (dir_context, project_dir, file) = sort_project(&temp_dir);
+
+    let mut harness = start(&dir_context, &project_dir, &file);
+    save_and_decide(&mut harness, 0);
+    save(&mut harness);
+    harness.assert_buffer_content(SORTED);
+    drop(harness);
+
+    // Asked again after a restart
+    std::fs::write(&file, UNSORTED).unwrap();
+    let mut harness = start(&dir_context, &project_dir, &file);
+    save_and_decide(&mut harness, 0);
+}
+
+#[test]
+#[cfg_attr(not(unix), ignore = "On-save actions require Unix-like environment")]
+fn test_always_allow_survives_restart() {
+    let temp_dir = TempDir::new().unwrap();
+    let (dir_context, project_dir, file) = sort_project(&temp_dir);
+
+    let mut harness = start(&dir_context, &project_dir, &file);
+    save_and_decide(&mut harness, 1);
+    save(&mut harness);
+    harness.assert_buffer_content(SORTED);
+    drop(harness);
+
+    std::fs::write(&file, UNSORTED).unwrap();
+    let mut harness = start(&dir_context, &project_dir, &file);
+    save(&mut harness);
+    harness.assert_buffer_content(SORTED);
+    assert!(!harness.editor_mut().check_command_trust_requests());
+}
+
+#[test]
+#[cfg_attr(not(unix), ignore = "On-save actions require Unix-like environment")]
+fn test_never_blocks_across_restart_until_reviewed() {
+    let temp_dir = TempDir::new().unwrap();
+    let (dir_context, project_dir, file) = sort_project(&temp_dir);
+
+    let mut harness = start(&dir_context, &project_dir, &file);
+    save_and_decide(&mut harness, 2);
+    harness.assert_screen_contains("⊘ 1 blocked");
+    save(&mut harness);
+    harness.assert_buffer_content(UNSORTED);
+    assert!(!harness.editor_mut().check_command_trust_requests());
+    drop(harness);
+
+    // Still blocked, without asking, after a restart
+    let mut harness = start(&dir_context, &project_dir, &file);
+    harness.render().unwrap();
+    harness.assert_screen_contains("⊘ 1 blocked");
+    save(&mut harness);
+    harness.assert_buffer_content(UNSORTED);
+    assert!(!harness.editor_mut().check_command_trust_requests());
+
+    // The review asks again; allowing it lets the formatter run
+    harness.editor_mut().review_blocked_commands();
+    harness.render().unwrap();
+    harness.assert_screen_contains("Blocked project commands");
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    harness.render().unwrap();
+    harness.assert_screen_contains("Run project command?");
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    harness.render().unwrap();
+    harness.assert_screen_not_contains("⊘ 1 blocked");
+    save(&mut harness);
+    harness.assert_buffer_content(SORTED);
+}
@@ -0,0 +1,116 @@
+//! Tests for key paths in JSON, YAML and TOML files
+
+use crate::common::harness::EditorTestHarness;
+use crossterm::event::{KeyCode, KeyModifiers};
+use fresh::config::{Config, DataPathBreadcrumb, DataPathStyle};
+use tempfile::TempDir;
+
+const DEPLOYMENT: &str = "\
+spec:
+  containers:
+    - name: web
+      image: nginx
+    - name: db
+      image: postgres
+";
+
+/// Harness showing `content` from a file named `name`
+fn open(config: Config, name: &str, content: &str) -> (EditorTestHarness, TempDir) {
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join(name);
+    std::fs::write(&path, content).unwrap();
+    let mut harness = EditorTestHarness::with_config(80, 24, config).unwrap();
+    harness.open_file(&path).unwrap();
+    harness.render().unwrap();
+    (harness, temp_dir)
+}
+
+/// Run a command from the command palette
+fn run_command(harness: &mut EditorTestHarness, name: &str) {
+    harness
+        .send_key(KeyCode::Char('p'), KeyModifiers::CONTROL)
+        .unwrap();
+    harness.type_text(name).unwrap();
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+}
+
+#[test]
+fn test_status_bar_shows_key_path() {
+    let (mut harness, _temp_dir) = open(Config::default(), "deploy.yaml", DEPLOYMENT);
+
+    harness
+        .editor_mut()
+        .goto_byte(DEPLOYMENT.find("postgres").unwrap());
+    harness.render().unwrap();
+    assert!(harness
+        .get_status_bar()
+        .contains("spec.containers[1].image"));
+
+    harness
+        .editor_mut()
+        .goto_byte(DEPLOYMENT.find("web").unwrap());
+    harness.render().unwrap();
+    harness.assert_screen_contains("spec.containers[0].name");
+    harness.assert_screen_not_contains("spec.containers[1]");
+}
+
+#[test]
+fn test_winbar_shows_key_path_in_jq_style() {
+    let mut config = Config::default();
+    config.editor.data_path_breadcrumb = DataPathBreadcrumb::Winbar;
+    config.editor.data_path_style = DataPathStyle::Jq;
+    let text = "{\"build\": {\"odd key\": [1, 2]}}\n";
+    let (mut harness, _temp_dir) = open(config, "package.json", text);
+
+    harness.editor_mut().goto_byte(text.find('2').unwrap());
+    harness.render().unwrap();
+    // Above the content, not in the status bar
+    let (first_row, _) = harness.content_area_rows();
+    assert!(harness
+        .get_screen_row(first_row)
+        .contains(".build.\"odd key\"[1]"));
+    assert!(!harness.get_status_bar().contains(".build"));
+    // The content starts on the row below
+    assert!(harness.get_screen_row(first_row + 1).contains("\"build\""));
+}
+
+#[test]
+fn test_goto_copy_and_select_key_path() {
+    let (mut harness, _temp_dir) = open(Config::default(), "deploy.yaml", DEPLOYMENT);
+
+    run_command(&mut harness, "Go to Key Path");
+    harness.type_text("spec.containers[1].name").unwrap();
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    assert_eq!(
+        harness.cursor_position(),
+        DEPLOYMENT.find("name: db").unwrap()
+    );
+
+    run_command(&mut harness, "Copy Key Path");
+    assert_eq!(
+        harness.editor().get_status_message().unwrap(),
+        "Copied path: spec.containers[1].name"
+    );
+
+    run_command(&mut harness, "Select Key Value");
+    assert_eq!(harness.get_selected_text(), "db");
+    run_command(&mut harness, "Select Key Value");
+    assert_eq!(
+        harness.get_selected_text(),
+        "name: db\n      image: postgres"
+    );
+
+    run_command(&mut harness, "Go to Key Path");
+    harness.type_text("spec.volumes").unwrap();
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    assert_eq!(
+        harness.editor().get_status_message().unwrap(),
+        "No key at spec.volumes"
+    );
+}
@@ -0,0 +1,78 @@
+//! Tests for plugin decorations colored by theme color name
+
+use crate::common::harness::EditorTestHarness;
+use fresh::services::plugins::api::PluginCommand;
+use fresh::view::theme::{DecorationColor, Theme};
+use ratatui::style::Color;
+
+/// Screen position of the first occurrence of `text`
+fn find_on_screen(harness: &EditorTestHarness, text: &str) -> (u16, u16) {
+    harness
+        .screen_to_string()
+        .lines()
+        .enumerate()
+        .find_map(|(row, line)| {
+            line.find(text)
+                .map(|byte| (line[..byte].chars().count() as u16, row as u16))
+        })
+        .unwrap_or_else(|| panic!("{text:?} not on screen"))
+}
+
+/// Color the first `len` bytes of the buffer with theme colors
+fn add_named_overlay(harness: &mut EditorTestHarness, len: usize, fg: &str, bg: Option<&str>) {
+    let buffer_id = harness.editor().active_buffer();
+    harness
+        .editor_mut()
+        .handle_plugin_command(PluginCommand::AddOverlay {
+            buffer_id,
+            namespace: None,
+            range: 0..len,
+            color: DecorationColor::Named(fg.to_string()),
+            bg_color: bg.map(|bg| DecorationColor::Named(bg.to_string())),
+            underline: false,
+            bold: false,
+            italic: false,
+            extend_to_line_end: false,
+        })
+        .unwrap();
+    harness.render().unwrap();
+}
+
+#[test]
+fn test_named_overlay_follows_theme_change() {
+    let mut harness = EditorTestHarness::new(80, 24).unwrap();
+    harness.type_text("added line").unwrap();
+    add_named_overlay(&mut harness, 5, "diff.added", Some("diff.added_bg"));
+
+    let (x, y) = find_on_screen(&harness, "added");
+    let style = harness.get_cell_style(x, y).unwrap();
+    let dark = Theme::high_contrast();
+    assert_eq!(style.fg, Some(dark.diff_added_fg));
+    assert_eq!(style.bg, Some(dark.diff_added_bg));
+
+    // Switching the theme recolors the overlay without a new plugin command
+    harness
+        .editor_mut()
+        .handle_plugin_command(PluginCommand::ApplyTheme {
+            theme_name: "light".to_string(),
+        })
+        .unwrap();
+    harness.render().unwrap();
+    let style = harness.get_cell_style(x, y).unwrap();
+    let light = Theme::light();
+    assert_ne!(light.diff_added_fg, dark.diff_added_fg);
+    assert_eq!(style.fg, Some(light.diff_added_fg));
+    assert_eq!(style.bg, Some(light.diff_added_bg));
+}
+
+#[test]
+fn test_unknown_color_name_falls_back_to_text_color() {
+    let mut harness = EditorTestHarness::new(80, 24).unwrap();
+    harness.type_text("hello").unwrap();
+    add_named_overlay(&mut harness, 5, "no.such_color", None);
+
+    let (x, y) = find_on_screen(&harness, "hello");
+    let style = harness.get_cell_style(x, y).unwrap();
+    assert_eq!(style.fg, Some(Theme::high_contrast().editor_fg));
+    assert_ne!(style.fg, Some(Color::Reset));
+}
@@ -0,0 +1,150 @@
+//! E2E tests for "Compare Directories…"
+
+use crate::common::harness::EditorTestHarness;
+use crossterm::event::{KeyCode, KeyModifiers};
+use fresh::services::dir_compare::FileStatus;
+use fresh::services::plugins::api::PluginCommand;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+fn execute_action(harness: &mut EditorTestHarness, name: &str) {
+    harness
+        .editor_mut()
+        .handle_plugin_command(PluginCommand::ExecuteAction {
+            action_name: name.to_string(),
+        })
+        .unwrap();
+    harness.render().unwrap();
+}
+
+fn write(root: &Path, path: &str, contents: &str) {
+    let path = root.join(path);
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(path, contents).unwrap();
+}
+
+/// Two trees with a file of each status, compared in the editor
+fn compare_trees(harness: &mut EditorTestHarness) -> (PathBuf, PathBuf) {
+    let project_root = harness.project_dir().unwrap();
+    let (left, right) = (project_root.join("before"), project_root.join("after"));
+    write(&left, "same.txt", "unchanged\n");
+    write(&right, "same.txt", "unchanged\n");
+    write(&left, "src/changed.rs", "fn one() {}\nfn two() {}\n");
+    write(&right, "src/changed.rs", "fn one() {}\nfn TWO() {}\n");
+    write(&left, "src/removed.rs", "gone\n");
+    write(&right, "src/added.rs", "new\n");
+
+    harness
+        .editor_mut()
+        .compare_directories(left.clone(), right.clone());
+    harness
+        .wait_until(|h| !h.editor().is_comparing_directories())
+        .unwrap();
+    harness.render().unwrap();
+    (left, right)
+}
+
+/// Move the cursor to the line of the comparison buffer holding `text`
+fn goto_line_with(harness: &mut EditorTestHarness, text: &str) {
+    let content = harness.get_buffer_content().unwrap();
+    let line = content
+        .lines()
+        .position(|line| line.contains(text))
+        .unwrap_or_else(|| panic!("no line with {:?} in {:?}", text, content));
+    harness.editor_mut().goto_line_col(line + 1, None);
+    harness.render().unwrap();
+}
+
+/// Each file gets its status, shown with its glyph under its directory
+#[test]
+fn test_compare_directories_lists_each_status() {
+    let mut harness = EditorTestHarness::with_temp_project(100, 30).unwrap();
+    compare_trees(&mut harness);
+
+    let statuses: Vec<(String, FileStatus)> = harness
+        .editor()
+        .dir_comparison()
+        .unwrap()
+        .files
+        .iter()
+        .map(|f| (f.path.to_string_lossy().replace('\\', "/"), f.status))
+        .collect();
+    assert_eq!(
+        statuses,
+        vec![
+            ("same.txt".to_string(), FileStatus::Identical),
+            ("src/added.rs".to_string(), FileStatus::OnlyRight),
+            ("src/changed.rs".to_string(), FileStatus::Different),
+            ("src/removed.rs".to_string(), FileStatus::OnlyLeft),
+        ]
+    );
+    harness.assert_screen_contains("./ (1)");
+    harness.assert_screen_contains("  = same.txt");
+    harness.assert_screen_contains("src/ (3)");
+    harness.assert_screen_contains("  + added.rs");
+    harness.assert_screen_contains("  ~ changed.rs");
+    harness.assert_screen_contains("  - removed.rs");
+}
+
+/// `i` hides the identical files and shows them again, and `g` compares
+/// the trees again in place
+#[test]
+fn test_compare_directories_hides_identical_and_refreshes() {
+    let mut harness = EditorTestHarness::with_temp_project(100, 30).unwrap();
+    let (_, right) = compare_trees(&mut harness);
+    let buffer_id = harness.editor().active_buffer();
+
+    execute_action(&mut harness, "dir_compare_toggle_identical");
+    harness.assert_screen_not_contains("same.txt");
+    harness.assert_screen_contains("  ~ changed.rs");
+
+    write(&right, "same.txt", "changed now\n");
+    harness
+        .send_key(KeyCode::Char('g'), KeyModifiers::NONE)
+        .unwrap();
+    harness
+        .wait_until(|h| !h.editor().is_comparing_directories())
+        .unwrap();
+    harness.render().unwrap();
+    assert_eq!(harness.editor().active_buffer(), buffer_id);
+    harness.assert_screen_contains("  ~ same.txt");
+
+    execute_action(&mut harness, "dir_compare_toggle_identical");
+    harness.assert_screen_contains("  ~ same.txt");
+}
+
+/// Enter on a differing file opens both sides in the buffer comparison,
+/// and on a file of one side opens that file
+#[test]
+fn test_compare_directories_opens_files() {
+    let mut harness = EditorTestHarness::with_temp_project(100, 30).unwrap();
+    let (left, right) = compare_trees(&mut harness);
+    let comparison_buffer = harness.editor().active_buffer();
+
+    goto_line_with(&mut harness, "~ changed.rs");
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    harness.render().unwrap();
+    let left_file = left.join("src/changed.rs");
+    assert_eq!(
+        harness.editor().active_state().buffer.file_path(),
+        Some(left_file.as_path())
+    );
+    assert_eq!(harness.editor().get_split_count(), 2);
+    assert_eq!(harness.editor().compare_hunks(), Some(vec![(1..2, 1..2)]));
+    harness.assert_screen_contains("fn TWO() {}");
+
+    harness.editor_mut().switch_buffer(comparison_buffer);
+    harness.render().unwrap();
+    goto_line_with(&mut harness, "+ added.rs");
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    harness.render().unwrap();
+    let added = right.join("src/added.rs");
+    assert_eq!(
+        harness.editor().active_state().buffer.file_path(),
+        Some(added.as_path())
+    );
+}
@@ -0,0 +1,139 @@
+//! E2E tests for distraction-free mode
+
+use crate::common::harness::EditorTestHarness;
+use crossterm::event::{KeyCode, KeyModifiers};
+use fresh::config::Config;
+use std::path::PathBuf;
+
+const PROSE: &str = "The quick brown fox jumps over the lazy dog and keeps running across \
+the field until the sun goes down behind the hills far away from the farm.\n\
+\n\
+A second paragraph.\n";
+
+fn open_prose(harness: &mut EditorTestHarness) -> PathBuf {
+    let path = harness.project_dir().unwrap().join("notes.txt");
+    std::fs::write(&path, PROSE).unwrap();
+    harness.open_file(&path).unwrap();
+    path
+}
+
+fn run_command(harness: &mut EditorTestHarness, name: &str) {
+    harness
+        .send_key(KeyCode::Char('p'), KeyModifiers::CONTROL)
+        .unwrap();
+    harness.type_text(name).unwrap();
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    harness.render().unwrap();
+}
+
+/// Number of leading spaces of a screen row
+fn indent(row: &str) -> usize {
+    row.chars().take_while(|c| *c == ' ').count()
+}
+
+/// The text sits in an 80 column wrapped column centered in the split, with
+/// no menu bar, tabs, gutter or status bar around it
+#[test]
+fn test_distraction_free_centers_text_and_hides_chrome() {
+    for width in [100u16, 140] {
+        let mut harness = EditorTestHarness::with_temp_project(width, 24).unwrap();
+        open_prose(&mut harness);
+        harness.assert_screen_contains("notes.txt");
+
+        run_command(&mut harness, "Toggle Distraction-Free Mode");
+        assert!(harness.editor().is_distraction_free());
+
+        // One column of the split is the scrollbar
+        let left_pad = ((width - 1 - 80) / 2) as usize;
+        let first = harness.get_screen_row(0);
+        assert_eq!(indent(&first), left_pad, "width {}: {:?}", width, first);
+        assert!(first[left_pad..].starts_with("The quick brown fox"));
+
+        // The first paragraph is wrapped inside the column
+        let second = harness.get_screen_row(1);
+        assert_eq!(indent(&second), left_pad, "width {}: {:?}", width, second);
+        for row in 0..3 {
+            let text = harness.get_screen_row(row);
+            assert!(text.trim_end().chars().count() <= left_pad + 80);
+        }
+        harness.assert_screen_contains("A second paragraph.");
+
+        harness.assert_screen_not_contains("notes.txt");
+        harness.assert_screen_not_contains("File");
+        assert!(!harness.get_screen_row(0).contains('│'));
+    }
+}
+
+/// Leaving the mode restores the splits, menu bar, tabs, line numbers and wrap
+#[test]
+fn test_distraction_free_restores_previous_layout() {
+    let mut harness = EditorTestHarness::with_temp_project(100, 24).unwrap();
+    open_prose(&mut harness);
+    harness.editor_mut().split_pane_vertical();
+    harness.render().unwrap();
+    assert_eq!(harness.editor().get_split_areas().len(), 2);
+    let wrap_before = harness.editor().active_viewport().line_wrap_enabled;
+    let menu_before = harness.get_menu_bar();
+    let tabs_before = harness.get_tab_bar();
+
+    harness.editor_mut().toggle_distraction_free();
+    harness.render().unwrap();
+    assert_eq!(harness.editor().get_split_areas().len(), 1);
+    assert!(harness.editor().active_viewport().line_wrap_enabled);
+    assert!(!harness.editor().active_state().margins.show_line_numbers);
+
+    harness.editor_mut().toggle_distraction_free();
+    harness.render().unwrap();
+    assert!(!harness.editor().is_distraction_free());
+    assert_eq!(harness.editor().get_split_areas().len(), 2);
+    assert_eq!(
+        harness.editor().active_viewport().line_wrap_enabled,
+        wrap_before
+    );
+    assert!(harness.editor().active_state().margins.show_line_numbers);
+    assert_eq!(harness.get_menu_bar(), menu_before);
+    assert_eq!(harness.get_tab_bar(), tabs_before);
+    harness.assert_screen_contains("notes.txt");
+}
+
+/// Sessions keep the normal layout unless the config asks to restore the mode
+#[test]
+fn test_distraction_free_not_saved_in_session_by_default() {
+    let mut harness = EditorTestHarness::with_temp_project(100, 24).unwrap();
+    open_prose(&mut harness);
+    harness.editor_mut().toggle_distraction_free();
+
+    let session = harness.editor().capture_session();
+    assert!(!session.distraction_free);
+    assert_eq!(session.config_overrides.menu_bar_hidden, Some(false));
+    assert!(session
+        .split_states
+        .values()
+        .all(|state| state.compose_width.is_none()));
+
+    let mut config = Config::default();
+    config.distraction_free.restore_with_session = true;
+    let mut harness = EditorTestHarness::with_temp_project_and_config(100, 24, config).unwrap();
+    open_prose(&mut harness);
+    harness.editor_mut().toggle_distraction_free();
+    assert!(harness.editor().capture_session().distraction_free);
+}
+
+/// With dimming on, only the paragraph with the cursor keeps its colors
+#[test]
+fn test_distraction_free_dims_other_paragraphs() {
+    let mut config = Config::default();
+    config.distraction_free.dim_other_paragraphs = true;
+    let mut harness = EditorTestHarness::with_temp_project_and_config(100, 24, config).unwrap();
+    open_prose(&mut harness);
+    harness.editor_mut().toggle_distraction_free();
+    harness.render().unwrap();
+
+    let left_pad = (100 - 1 - 80) / 2;
+    let current = harness.get_cell_style(left_pad, 0).unwrap();
+    let other = harness.get_cell_style(left_pad, 3).unwrap();
+    assert_eq!(harness.get_cell(left_pad, 3).as_deref(), Some("A"));
+    assert_ne!(current.bg, other.bg);
+}
@@ -0,0 +1,108 @@
+//! E2E tests for dry runs of palette commands (Ctrl+Enter)
+
+use crate::common::harness::EditorTestHarness;
+use crossterm::event::{KeyCode, KeyModifiers};
+use fresh::model::pending_edits::PendingEdit;
+
+fn run_command(harness: &mut EditorTestHarness, name: &str, modifiers: KeyModifiers) {
+    harness
+        .send_key(KeyCode::Char('p'), KeyModifiers::CONTROL)
+        .unwrap();
+    harness.type_text(name).unwrap();
+    harness.send_key(KeyCode::Enter, modifiers).unwrap();
+    harness.render().unwrap();
+}
+
+/// The latest dry-run report in the notification history
+fn dry_run_report(harness: &EditorTestHarness) -> String {
+    harness
+        .editor()
+        .notifications()
+        .history()
+        .rev()
+        .map(|notification| notification.message.clone())
+        .find(|message| message.starts_with("Dry run:"))
+        .expect("a dry-run report")
+}
+
+/// A dry run of Close Saved Buffers lists the saved buffers and closes none
+#[test]
+fn test_dry_run_close_saved_buffers() {
+    let mut harness = EditorTestHarness::with_temp_project(120, 30).unwrap();
+    let project = harness.project_dir().unwrap();
+    for name in ["alpha.txt", "beta.txt", "gamma.txt"] {
+        std::fs::write(project.join(name), "text\n").unwrap();
+        harness.open_file(&project.join(name)).unwrap();
+    }
+    // gamma.txt has unsaved changes
+    harness.type_text("more ").unwrap();
+
+    run_command(&mut harness, "Close Saved Buffers", KeyModifiers::CONTROL);
+
+    let report = dry_run_report(&harness);
+    assert!(report.contains("would close 2 buffer(s)"), "{}", report);
+    assert!(report.contains("alpha.txt"), "{}", report);
+    assert!(report.contains("beta.txt"), "{}", report);
+    assert!(!report.contains("gamma.txt"), "{}", report);
+    harness.assert_screen_contains("alpha.txt");
+    harness.assert_screen_contains("beta.txt");
+
+    // Without the dry run they are closed
+    run_command(&mut harness, "Close Saved Buffers", KeyModifiers::NONE);
+    harness.assert_screen_contains("Closed 2 saved buffer(s)");
+    assert_eq!(harness.get_buffer_content().unwrap(), "more text\n");
+}
+
+/// A dry run of Apply Pending Edits reports the edits per file, changes
+/// nothing, and keeps the review open
+#[test]
+fn test_dry_run_apply_pending_edits() {
+    let mut harness = EditorTestHarness::with_temp_project(120, 30).unwrap();
+    let project = harness.project_dir().unwrap();
+    let a = project.join("a.txt");
+    let b = project.join("b.txt");
+    std::fs::write(&a, "foo one\nfoo two\n").unwrap();
+    std::fs::write(&b, "foo three\n").unwrap();
+    harness.open_file(&a).unwrap();
+
+    let edit = |file: &std::path::Path, start: usize| PendingEdit {
+        file: file.to_path_buf(),
+        range: start..start + 3,
+        old_text: "foo".to_string(),
+        new_text: "bar".to_string(),
+        enabled: true,
+    };
+    let edits = vec![edit(&a, 0), edit(&a, 8), edit(&b, 0)];
+    harness
+        .editor_mut()
+        .review_pending_edits("Replace foo".to_string(), edits);
+    harness.render().unwrap();
+
+    run_command(&mut harness, "Apply Pending Edits", KeyModifiers::CONTROL);
+
+    let report = dry_run_report(&harness);
+    assert!(
+        report.contains("would make 3 edit(s) in 2 file(s)"),
+        "{}",
+        report
+    );
+    assert!(report.contains("a.txt: 2 edit(s)"), "{}", report);
+    assert!(report.contains("b.txt: 1 edit(s)"), "{}", report);
+
+    harness.assert_screen_contains("Pending edits: Replace foo");
+    assert_eq!(std::fs::read_to_string(&a).unwrap(), "foo one\nfoo two\n");
+    assert_eq!(std::fs::read_to_string(&b).unwrap(), "foo three\n");
+    harness.open_file(&a).unwrap();
+    assert_eq!(harness.get_buffer_content().unwrap(), "foo one\nfoo two\n");
+}
+
+/// Commands without dry-run support run normally with Ctrl+Enter
+#[test]
+fn test_dry_run_unsupported_command_runs() {
+    let mut harness = EditorTestHarness::with_temp_project(120, 30).unwrap();
+    harness.type_text("hello").unwrap();
+
+    run_command(&mut harness, "Select All", KeyModifiers::CONTROL);
+    harness.type_text("x").unwrap();
+    assert_eq!(harness.get_buffer_content().unwrap(), "x");
+}
@@ -0,0 +1,99 @@
+//! E2E tests for viewing the editor's own log
+
+use crate::common::harness::EditorTestHarness;
+use crossterm::event::{KeyCode, KeyModifiers};
+use fresh::services::rotating_log::{LogLimits, RotatingLog};
+use std::io::Write;
+use std::path::Path;
+use std::time::Duration;
+use tempfile::TempDir;
+
+/// Append `lines` to the log and wait until they are on disk
+fn write_log(log: &RotatingLog, lines: &[&str]) {
+    let mut writer = log;
+    for line in lines {
+        writer.write_all(format!("{line}\n").as_bytes()).unwrap();
+    }
+    log.flush();
+}
+
+fn harness_with_log(path: &Path) -> (EditorTestHarness, RotatingLog) {
+    let log = RotatingLog::create(path, LogLimits::default()).unwrap();
+    write_log(
+        &log,
+        &[
+            "2026-01-02T03:04:05.000001Z  INFO fresh::app: started",
+            "2026-01-02T03:04:06.000001Z  WARN fresh::lsp: server is slow",
+            "2026-01-02T03:04:07.000001Z ERROR fresh::lsp: server crashed",
+            "  with a second line",
+            "2026-01-02T03:04:08.000001Z  INFO fresh::app: still running",
+        ],
+    );
+    let mut harness = EditorTestHarness::new(100, 24).unwrap();
+    harness.editor_mut().set_log_file(log.clone());
+    (harness, log)
+}
+
+/// The log buffer shows the file, follows new lines and hides the levels
+/// below the filter
+#[test]
+fn test_editor_log_follows_and_filters() {
+    let temp_dir = TempDir::new().unwrap();
+    let (mut harness, log) = harness_with_log(&temp_dir.path().join("fresh.log"));
+
+    harness.editor_mut().open_editor_log();
+    harness.render().unwrap();
+    harness.assert_screen_contains("*Editor Log*");
+    let content = harness.get_buffer_content().unwrap();
+    assert!(content.contains("INFO fresh::app: started"), "{content}");
+    assert!(content.contains("  with a second line"), "{content}");
+    assert_eq!(harness.cursor_position(), content.len());
+
+    write_log(
+        &log,
+        &["2026-01-02T03:04:09.000001Z DEBUG fresh::app: later"],
+    );
+    harness.advance_time(Duration::from_secs(1));
+    harness.process_async_and_render().unwrap();
+    let content = harness.get_buffer_content().unwrap();
+    assert!(content.ends_with("DEBUG fresh::app: later\n"), "{content}");
+    assert_eq!(harness.cursor_position(), content.len());
+
+    harness.editor_mut().start_editor_log_level_prompt();
+    harness.type_text("WARN").unwrap();
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    let content = harness.get_buffer_content().unwrap();
+    assert_eq!(
+        content,
+        "2026-01-02T03:04:06.000001Z  WARN fresh::lsp: server is slow\n\
+         2026-01-02T03:04:07.000001Z ERROR fresh::lsp: server crashed\n  \
+         with a second line\n"
+    );
+}
+
+/// A line of the warning log opens the full log at the same event
+#[test]
+fn test_open_editor_log_at_warning_entry() {
+    let temp_dir = TempDir::new().unwrap();
+    let (mut harness, _log) = harness_with_log(&temp_dir.path().join("fresh.log"));
+    let warnings_path = temp_dir.path().join("warnings.log");
+    std::fs::write(
+        &warnings_path,
+        "2026-01-02 03:04:06.000 WARN fresh::lsp: server is slow (suppressed 2 similar messages)\n",
+    )
+    .unwrap();
+    let (_sender, receiver) = std::sync::mpsc::channel();
+    harness
+        .editor_mut()
+        .set_warning_log(receiver, warnings_path.clone());
+    harness.open_file(&warnings_path).unwrap();
+
+    harness.editor_mut().open_editor_log_at_entry();
+    harness.render().unwrap();
+    harness.assert_screen_contains("*Editor Log*");
+    let content = harness.get_buffer_content().unwrap();
+    let warning = content.find("2026-01-02T03:04:06").unwrap();
+    assert_eq!(harness.cursor_position(), warning);
+}
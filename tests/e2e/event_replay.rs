@@ -0,0 +1,62 @@
+//! Replaying the keystrokes of an event log on the virtual clock
+
+use crate::common::harness::EditorTestHarness;
+use crossterm::event::{KeyCode, KeyModifiers};
+use fresh::app::RecordedKeystroke;
+use fresh::config::Config;
+use std::path::Path;
+use std::time::Duration;
+use tempfile::TempDir;
+
+/// Record typing `keys`, each pressed at the given time, to the event log
+/// at `log`
+fn record(log: &Path, keys: &[(char, u64)]) {
+    let mut harness = EditorTestHarness::new(80, 24).unwrap();
+    harness.editor_mut().enable_event_streaming(log).unwrap();
+    for &(key, at_secs) in keys {
+        let now = harness.time_source().elapsed();
+        harness.advance_time(Duration::from_secs(at_secs) - now);
+        // As the event loop does for every key it reads
+        harness
+            .editor_mut()
+            .log_keystroke(KeyCode::Char(key), KeyModifiers::NONE);
+        harness
+            .send_key(KeyCode::Char(key), KeyModifiers::NONE)
+            .unwrap();
+    }
+}
+
+/// Replay the event log at `log` in an editor that auto-saves every 10s
+fn replay(log: &Path) -> EditorTestHarness {
+    let mut config = Config::default();
+    config.editor.auto_save_interval_secs = 10;
+    let mut harness = EditorTestHarness::with_config(80, 24, config).unwrap();
+    let keys = RecordedKeystroke::read_all(log).unwrap();
+    let clock = harness.time_source().clone();
+    harness
+        .editor_mut()
+        .replay_keystrokes(&keys, |duration| clock.advance(duration))
+        .unwrap();
+    harness
+}
+
+#[test]
+fn test_replay_runs_timers_at_the_recorded_times() {
+    let temp_dir = TempDir::new().unwrap();
+
+    // Typed within the auto-save interval: nothing is auto-saved
+    let log = temp_dir.path().join("quick.log");
+    record(&log, &[('a', 1), ('b', 9)]);
+    let harness = replay(&log);
+    harness.assert_buffer_content("ab");
+    assert_eq!(harness.time_source().elapsed(), Duration::from_secs(9));
+    assert_eq!(harness.editor().list_recoverable_files().unwrap().len(), 0);
+
+    // A pause past the interval before the last key: the auto-save fires
+    let log = temp_dir.path().join("paused.log");
+    record(&log, &[('a', 1), ('b', 9), ('c', 12)]);
+    let harness = replay(&log);
+    harness.assert_buffer_content("abc");
+    assert_eq!(harness.time_source().elapsed(), Duration::from_secs(12));
+    assert_eq!(harness.editor().list_recoverable_files().unwrap().len(), 1);
+}
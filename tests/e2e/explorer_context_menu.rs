@@ -0,0 +1,128 @@
+//! E2E tests for the file explorer's context menu
+
+use crate::common::harness::EditorTestHarness;
+use crossterm::event::{KeyCode, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+use fresh::input::keybindings::KeyContext;
+use std::fs;
+
+/// A project with src/lib.rs and notes.txt, shown in the focused explorer
+fn setup() -> EditorTestHarness {
+    let mut harness = EditorTestHarness::with_temp_project(120, 30).unwrap();
+    let project_dir = harness.project_dir().unwrap();
+    fs::create_dir(project_dir.join("src")).unwrap();
+    fs::write(project_dir.join("src/lib.rs"), "").unwrap();
+    fs::write(project_dir.join("notes.txt"), "my notes\n").unwrap();
+    harness.editor_mut().focus_file_explorer();
+    harness.wait_for_file_explorer_item("notes.txt").unwrap();
+    harness
+}
+
+/// Screen column and row of an explorer entry
+fn entry_position(harness: &EditorTestHarness, name: &str) -> (u16, u16) {
+    harness
+        .screen_to_string()
+        .lines()
+        .enumerate()
+        .skip(1)
+        .find_map(|(row, line)| {
+            let byte = line.find(name)?;
+            Some((line[..byte].chars().count() as u16, row as u16))
+        })
+        .unwrap_or_else(|| panic!("'{}' is not on screen", name))
+}
+
+fn right_click(harness: &mut EditorTestHarness, (col, row): (u16, u16)) {
+    harness
+        .send_mouse(MouseEvent {
+            kind: MouseEventKind::Down(MouseButton::Right),
+            column: col,
+            row,
+            modifiers: KeyModifiers::NONE,
+        })
+        .unwrap();
+    harness.render().unwrap();
+}
+
+fn selected_name(harness: &EditorTestHarness) -> String {
+    let info = harness.editor().file_explorer_info().unwrap();
+    let path = info.selected_path.unwrap();
+    path.file_name().unwrap().to_string_lossy().to_string()
+}
+
+/// Right-clicking selects the entry; files get "Open in Split", directories
+/// don't, and Escape closes the menu
+#[test]
+fn test_context_menu_on_file_and_directory() {
+    let mut harness = setup();
+
+    let notes = entry_position(&harness, "notes.txt");
+    right_click(&mut harness, notes);
+    assert_eq!(selected_name(&harness), "notes.txt");
+    for label in [
+        "Open in Split",
+        "Rename",
+        "Delete",
+        "Copy Path",
+        "Reveal in File Manager",
+    ] {
+        harness.assert_screen_contains(label);
+    }
+    harness.send_key(KeyCode::Esc, KeyModifiers::NONE).unwrap();
+    harness.assert_screen_not_contains("Copy Path");
+
+    let src = entry_position(&harness, "src");
+    right_click(&mut harness, src);
+    assert_eq!(selected_name(&harness), "src");
+    harness.assert_screen_contains("Copy Path");
+    harness.assert_screen_contains("Rename");
+    harness.assert_screen_not_contains("Open in Split");
+
+    // A right-click elsewhere closes it
+    right_click(&mut harness, (100, 20));
+    harness.assert_screen_not_contains("Copy Path");
+}
+
+/// Copy Path closes the menu and says which path it copied
+#[test]
+fn test_context_menu_copy_path() {
+    let mut harness = setup();
+
+    let notes = entry_position(&harness, "notes.txt");
+    right_click(&mut harness, notes);
+    let copy = entry_position(&harness, "Copy Path");
+    harness.mouse_click(copy.0, copy.1).unwrap();
+
+    harness.assert_screen_not_contains("Copy Path");
+    let status = harness.get_status_bar();
+    assert!(status.contains("Copied path:"), "status: {}", status);
+    assert!(status.contains("notes.txt"), "status: {}", status);
+}
+
+/// The menu key opens the menu on the selected entry and the arrows, Enter
+/// and Escape drive it, with the explorer keeping the keys once it's closed
+#[test]
+fn test_context_menu_keyboard_only() {
+    let mut harness = setup();
+    while selected_name(&harness) != "notes.txt" {
+        harness.send_key(KeyCode::Down, KeyModifiers::NONE).unwrap();
+    }
+
+    harness
+        .send_key(KeyCode::F(10), KeyModifiers::SHIFT)
+        .unwrap();
+    harness.assert_screen_contains("Open in Split");
+    harness.send_key(KeyCode::Esc, KeyModifiers::NONE).unwrap();
+    harness.assert_screen_not_contains("Open in Split");
+    assert_eq!(harness.editor().get_key_context(), KeyContext::FileExplorer);
+
+    // Second entry: Open in Split
+    let splits = harness.editor().get_split_count();
+    harness.send_key(KeyCode::Menu, KeyModifiers::NONE).unwrap();
+    harness.send_key(KeyCode::Down, KeyModifiers::NONE).unwrap();
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    assert_eq!(harness.editor().get_split_count(), splits + 1);
+    assert_eq!(harness.get_buffer_content().unwrap(), "my notes\n");
+    assert_eq!(harness.editor().get_key_context(), KeyContext::Normal);
+}
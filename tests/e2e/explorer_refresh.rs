@@ -0,0 +1,238 @@
+//! E2E tests for batched file explorer updates during storms of file changes
+
+use crate::common::harness::{EditorTestHarness, HarnessOptions};
+use fresh::config::Config;
+use fresh::services::path_watch::{PathChange, PathChangeKind};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+const DIRS: usize = 50;
+const KEPT_FILES: usize = 5;
+/// Directories expanded in the explorer; dir03 is removed by the storm
+const EXPANDED: [&str; 5] = ["dir00", "dir01", "dir02", "dir03", "dir04"];
+
+/// A project of 50 directories of 5 files each, with the first five
+/// directories expanded in the explorer. Returns the explorer's root.
+fn setup() -> (EditorTestHarness, PathBuf) {
+    let mut config = Config::default();
+    // Only the injected changes reach the explorer
+    config.editor.file_tree_poll_interval_ms = 3_600_000;
+    let mut harness = EditorTestHarness::create(
+        120,
+        40,
+        HarnessOptions::new()
+            .with_project_root()
+            .with_config(config),
+    )
+    .unwrap();
+    let project_dir = harness.project_dir().unwrap();
+    for d in 0..DIRS {
+        let dir = project_dir.join(format!("dir{:02}", d));
+        fs::create_dir(&dir).unwrap();
+        for f in 0..KEPT_FILES {
+            fs::write(dir.join(format!("keep{}.txt", f)), "x").unwrap();
+        }
+    }
+
+    harness.editor_mut().focus_file_explorer();
+    // dir49 is listed below the rows the explorer shows
+    harness
+        .wait_until(|h| {
+            h.editor().file_explorer().is_some_and(|explorer| {
+                let tree = explorer.tree();
+                tree.get_node_by_path(&tree.root_path().join("dir49"))
+                    .is_some()
+            })
+        })
+        .unwrap();
+    let root = explorer_root(&harness);
+    for name in EXPANDED {
+        let dir = root.join(name);
+        select(&mut harness, &dir);
+        harness.editor_mut().file_explorer_toggle_expand();
+        harness
+            .wait_until(|h| {
+                h.editor()
+                    .file_explorer()
+                    .unwrap()
+                    .tree()
+                    .get_node_by_path(&dir)
+                    .is_some_and(|node| node.is_expanded())
+            })
+            .unwrap();
+    }
+    (harness, root)
+}
+
+fn explorer_root(harness: &EditorTestHarness) -> PathBuf {
+    let explorer = harness.editor().file_explorer().unwrap();
+    explorer.tree().root_path().to_path_buf()
+}
+
+fn selected_path(harness: &EditorTestHarness) -> Option<PathBuf> {
+    harness.editor().file_explorer_info()?.selected_path
+}
+
+/// Move the explorer's selection to a visible entry
+fn select(harness: &mut EditorTestHarness, path: &Path) {
+    for _ in 0..1000 {
+        let explorer = harness.editor().file_explorer().unwrap();
+        let tree = explorer.tree();
+        let target = tree
+            .get_visible_nodes()
+            .iter()
+            .position(|&id| tree.get_node(id).unwrap().entry.path == path)
+            .unwrap_or_else(|| panic!("{:?} is not visible", path));
+        let current = explorer.get_selected_index().unwrap();
+        if current == target {
+            return;
+        }
+        if current < target {
+            harness.editor_mut().file_explorer_navigate_down();
+        } else {
+            harness.editor_mut().file_explorer_navigate_up();
+        }
+    }
+    panic!("could not select {:?}", path);
+}
+
+/// Change every directory on disk and describe it with 10,000 events: per
+/// directory 20 new files, 40 files created and removed again, and 100
+/// modifications of the kept files. dir01/keep2.txt and dir03 are removed.
+fn storm(root: &Path) -> Vec<PathChange> {
+    let change = |path: PathBuf, kind| PathChange { path, kind };
+    let mut events = Vec::new();
+    for d in 0..DIRS {
+        let dir = root.join(format!("dir{:02}", d));
+        for i in 0..20 {
+            let path = dir.join(format!("new{:02}.txt", i));
+            fs::write(&path, "new").unwrap();
+            events.push(change(path, PathChangeKind::Created));
+        }
+        for i in 0..40 {
+            let path = dir.join(format!("tmp{:02}.txt", i));
+            events.push(change(path.clone(), PathChangeKind::Created));
+            events.push(change(path, PathChangeKind::Removed));
+        }
+        for f in 0..KEPT_FILES {
+            let path = dir.join(format!("keep{}.txt", f));
+            fs::write(&path, "modified").unwrap();
+            for _ in 0..20 {
+                events.push(change(path.clone(), PathChangeKind::Modified));
+            }
+        }
+    }
+    assert_eq!(events.len(), 10_000);
+
+    let selected = root.join("dir01/keep2.txt");
+    fs::remove_file(&selected).unwrap();
+    events.push(change(selected, PathChangeKind::Removed));
+    fs::remove_dir_all(root.join("dir03")).unwrap();
+    events.push(change(root.join("dir03"), PathChangeKind::Removed));
+    events
+}
+
+/// Queue the events in chunks, 10ms apart, all within one debounce window
+fn inject(harness: &mut EditorTestHarness, events: Vec<PathChange>) {
+    for chunk in events.chunks(1000) {
+        harness
+            .editor_mut()
+            .queue_file_explorer_changes(chunk.to_vec());
+        harness.advance_time(Duration::from_millis(10));
+        harness.process_async_and_render().unwrap();
+    }
+}
+
+/// Every loaded directory of the tree lists exactly what's on disk
+fn assert_tree_matches_disk(harness: &EditorTestHarness) {
+    let tree = harness.editor().file_explorer().unwrap().tree();
+    let mut checked = 0;
+    for node in tree.all_nodes().filter(|node| node.is_expanded()) {
+        let mut on_disk: Vec<String> = fs::read_dir(&node.entry.path)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+            .collect();
+        on_disk.sort();
+        let mut in_tree: Vec<String> = node
+            .children
+            .iter()
+            .map(|&id| tree.get_node(id).unwrap().entry.name.clone())
+            .collect();
+        in_tree.sort();
+        assert_eq!(in_tree, on_disk, "entries of {:?}", node.entry.path);
+        checked += 1;
+    }
+    // The root and the four expanded directories left
+    assert_eq!(checked, 5);
+}
+
+/// 10,000 events become one batch of in-place changes to the expanded
+/// directories; the selection and scroll position stay put
+#[test]
+fn test_event_storm_applies_one_batch() {
+    let (mut harness, root) = setup();
+    let selected = root.join("dir00/keep0.txt");
+    select(&mut harness, &selected);
+    let selected_id = harness.editor().file_explorer().unwrap().get_selected();
+    let scroll = harness
+        .editor()
+        .file_explorer()
+        .unwrap()
+        .get_scroll_offset();
+
+    inject(&mut harness, storm(&root));
+    assert_eq!(harness.editor().file_explorer_refresh_stats().batches, 0);
+
+    harness.advance_time(Duration::from_millis(200));
+    harness.process_async_and_render().unwrap();
+    let stats = harness.editor().file_explorer_refresh_stats();
+    assert_eq!(stats.batches, 1);
+    // Four expanded directories with 20 new and 5 changed or removed
+    // entries each, and dir03 removed from the root. The other 45 only
+    // drop their entry count.
+    assert_eq!(stats.mutations, 4 * 25 + 1);
+
+    let explorer = harness.editor().file_explorer().unwrap();
+    assert_eq!(explorer.get_selected(), selected_id);
+    assert_eq!(explorer.get_scroll_offset(), scroll);
+    assert_eq!(selected_path(&harness), Some(selected));
+    assert_tree_matches_disk(&harness);
+
+    // Nothing left over for another batch
+    harness.advance_time(Duration::from_millis(500));
+    harness.process_async_and_render().unwrap();
+    assert_eq!(harness.editor().file_explorer_refresh_stats().batches, 1);
+}
+
+/// When the selected file is removed, its next sibling is selected; a
+/// collapsed directory shows the new files once expanded
+#[test]
+fn test_removed_selection_moves_to_sibling() {
+    let (mut harness, root) = setup();
+    select(&mut harness, &root.join("dir01/keep2.txt"));
+
+    inject(&mut harness, storm(&root));
+    harness.advance_time(Duration::from_millis(200));
+    harness.process_async_and_render().unwrap();
+
+    assert_eq!(selected_path(&harness), Some(root.join("dir01/keep3.txt")));
+    assert_tree_matches_disk(&harness);
+
+    let dir10 = root.join("dir10");
+    select(&mut harness, &dir10);
+    harness.editor_mut().file_explorer_toggle_expand();
+    harness
+        .wait_until(|h| {
+            h.editor()
+                .file_explorer()
+                .unwrap()
+                .tree()
+                .get_node_by_path(&dir10.join("new19.txt"))
+                .is_some()
+        })
+        .unwrap();
+    let tree = harness.editor().file_explorer().unwrap().tree();
+    let dir10 = tree.get_node_by_path(&dir10).unwrap();
+    assert_eq!(dir10.children.len(), KEPT_FILES + 20);
+}
@@ -1852,3 +1852,113 @@ fn test_folder_modified_indicator_cleared_after_save() {
         mydir_line_after
     );
 }
+
+/// A plugin reveal expands the parents and selects the entry, even with the
+/// explorer hidden
+#[test]
+fn test_plugin_reveal_in_file_explorer() {
+    use fresh::services::plugins::api::PluginCommand;
+    let mut harness = EditorTestHarness::with_temp_project(120, 40).unwrap();
+    let project_dir = harness.project_dir().unwrap();
+    fs::create_dir_all(project_dir.join("src/nested")).unwrap();
+    fs::write(project_dir.join("src/nested/deep.rs"), "").unwrap();
+    fs::write(project_dir.join("README.md"), "").unwrap();
+
+    assert!(harness.editor().file_explorer_info().is_none());
+    harness
+        .editor_mut()
+        .handle_plugin_command(PluginCommand::RevealInFileExplorer {
+            path: "src/nested/deep.rs".into(),
+        })
+        .unwrap();
+    harness
+        .wait_until(|h| {
+            h.editor().file_explorer_info().is_some_and(|info| {
+                info.selected_path
+                    .is_some_and(|path| path.ends_with("src/nested/deep.rs"))
+            })
+        })
+        .unwrap();
+
+    let info = harness.editor().file_explorer_info().unwrap();
+    assert!(!info.visible);
+    assert!(!info.selected_is_dir);
+    assert!(info.expanded_paths.contains(&info.root));
+    assert!(info.expanded_paths.contains(&info.root.join("src")));
+    assert!(info.expanded_paths.contains(&info.root.join("src/nested")));
+
+    // Paths outside the explorer root leave the selection alone
+    harness
+        .editor_mut()
+        .handle_plugin_command(PluginCommand::RevealInFileExplorer {
+            path: "/definitely/not/in/the/project".into(),
+        })
+        .unwrap();
+    harness.process_async_and_render().unwrap();
+    assert_eq!(harness.editor().file_explorer_info().unwrap(), info);
+}
+
+/// Re-rooting the explorer requires an existing directory and keeps the
+/// working directory
+#[test]
+fn test_plugin_set_file_explorer_root() {
+    use fresh::services::plugins::api::PluginCommand;
+    let mut harness = EditorTestHarness::with_temp_project(120, 40).unwrap();
+    let project_dir = harness.project_dir().unwrap();
+    fs::create_dir_all(project_dir.join("src/nested")).unwrap();
+    fs::write(project_dir.join("src/lib.rs"), "").unwrap();
+    fs::write(project_dir.join("README.md"), "").unwrap();
+
+    harness.editor_mut().toggle_file_explorer();
+    harness.wait_for_file_explorer().unwrap();
+    let root = harness.editor().file_explorer_info().unwrap().root;
+
+    for bad in ["README.md", "missing"] {
+        harness
+            .editor_mut()
+            .handle_plugin_command(PluginCommand::SetFileExplorerRoot { path: bad.into() })
+            .unwrap();
+        harness.process_async_and_render().unwrap();
+        assert!(harness
+            .editor()
+            .get_status_message()
+            .is_some_and(|msg| msg.contains(bad)));
+        assert_eq!(harness.editor().file_explorer_info().unwrap().root, root);
+    }
+
+    harness
+        .editor_mut()
+        .handle_plugin_command(PluginCommand::SetFileExplorerRoot { path: "src".into() })
+        .unwrap();
+    harness
+        .wait_until(|h| {
+            h.editor()
+                .file_explorer_info()
+                .is_some_and(|info| info.root.ends_with("src"))
+        })
+        .unwrap();
+    harness.wait_for_file_explorer_item("lib.rs").unwrap();
+    harness.assert_screen_not_contains("README.md");
+
+    // Reveals are limited to the new root
+    harness
+        .editor_mut()
+        .handle_plugin_command(PluginCommand::RevealInFileExplorer {
+            path: "README.md".into(),
+        })
+        .unwrap();
+    harness
+        .editor_mut()
+        .handle_plugin_command(PluginCommand::RevealInFileExplorer {
+            path: "src/lib.rs".into(),
+        })
+        .unwrap();
+    harness
+        .wait_until(|h| {
+            h.editor().file_explorer_info().is_some_and(|info| {
+                info.selected_path
+                    .is_some_and(|path| path.ends_with("src/lib.rs"))
+            })
+        })
+        .unwrap();
+}
@@ -0,0 +1,259 @@
+//! Tests for the file explorer's details columns and sort cycling
+
+use crate::common::harness::{EditorTestHarness, HarnessOptions};
+use crossterm::event::{KeyCode, KeyModifiers};
+use fresh::config::Config;
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+
+const LONG_NAME: &str = "quarterly-report-final-v2.txt";
+
+/// Write `size` bytes to `path`, last modified `age` ago
+fn write_file(path: &Path, size: usize, age: Duration) {
+    fs::write(path, "x".repeat(size)).unwrap();
+    fs::File::options()
+        .write(true)
+        .open(path)
+        .unwrap()
+        .set_modified(SystemTime::now() - age)
+        .unwrap();
+}
+
+fn harness_with_explorer(width: u16, config: Config) -> EditorTestHarness {
+    let mut harness = EditorTestHarness::create(
+        width,
+        30,
+        HarnessOptions::new()
+            .with_project_root()
+            .with_config(config),
+    )
+    .unwrap();
+    let project = harness.project_dir().unwrap();
+    fs::create_dir(project.join("src")).unwrap();
+    write_file(
+        &project.join("alpha.txt"),
+        2048,
+        Duration::from_secs(5 * 60),
+    );
+    write_file(&project.join(LONG_NAME), 10, Duration::from_secs(3 * 3600));
+
+    harness.editor_mut().focus_file_explorer();
+    harness.wait_for_file_explorer().unwrap();
+    harness.wait_for_file_explorer_item("alpha.txt").unwrap();
+    harness
+}
+
+fn details_harness(width: u16) -> EditorTestHarness {
+    let mut config = Config::default();
+    config.file_explorer.show_details = true;
+    config.file_explorer.width = 0.4;
+    harness_with_explorer(width, config)
+}
+
+fn screen_line(harness: &EditorTestHarness, text: &str) -> String {
+    harness
+        .screen_to_string()
+        .lines()
+        .find(|line| line.contains(text))
+        .unwrap_or_else(|| panic!("no line with {text:?}"))
+        .to_string()
+}
+
+/// Screen column right after `text` on `line`
+fn column_after(line: &str, text: &str) -> usize {
+    let start = line.find(text).unwrap();
+    line[..start].chars().count() + text.chars().count()
+}
+
+#[test]
+fn test_details_columns_on_wide_explorer() {
+    let harness = details_harness(140);
+
+    let header = screen_line(&harness, "Name ▲");
+    let alpha = screen_line(&harness, "alpha.txt");
+    let report = screen_line(&harness, LONG_NAME);
+    assert!(
+        alpha.contains("2.0 KB") && alpha.contains("5m ago"),
+        "{alpha}"
+    );
+    assert!(
+        report.contains("0.0 KB") && report.contains("3h ago"),
+        "{report}"
+    );
+
+    // Sizes and times are right-aligned under their headings
+    let size_end = column_after(&header, "Size");
+    assert_eq!(column_after(&alpha, "2.0 KB"), size_end);
+    assert_eq!(column_after(&report, "0.0 KB"), size_end);
+    let time_end = column_after(&header, "Modified");
+    assert_eq!(column_after(&alpha, "5m ago"), time_end);
+    assert_eq!(column_after(&report, "3h ago"), time_end);
+}
+
+#[test]
+fn test_details_columns_dropped_on_narrow_explorer() {
+    let harness = details_harness(60);
+
+    // The columns go before the name is cut short, which it then is
+    let screen = harness.screen_to_string();
+    harness.assert_screen_contains("Name ▲");
+    assert!(
+        !screen.contains("Modified") && !screen.contains("KB"),
+        "{screen}"
+    );
+    harness.assert_screen_contains("quarterly-report…");
+}
+
+/// Names of `names` in the order they are shown
+fn shown_order<'a>(harness: &EditorTestHarness, names: &[&'a str]) -> Vec<&'a str> {
+    let screen = harness.screen_to_string();
+    let lines: Vec<&str> = screen.lines().collect();
+    let mut order: Vec<(usize, &str)> = names
+        .iter()
+        .filter_map(|name| {
+            let row = lines.iter().position(|line| line.contains(name))?;
+            Some((row, *name))
+        })
+        .collect();
+    order.sort();
+    order.into_iter().map(|(_, name)| name).collect()
+}
+
+#[test]
+fn test_cycle_sort_orders_each_directory() {
+    let mut harness =
+        EditorTestHarness::create(100, 30, HarnessOptions::new().with_project_root()).unwrap();
+    let project = harness.project_dir().unwrap();
+    let minute = Duration::from_secs(60);
+    write_file(&project.join("a_small.log"), 10, minute);
+    write_file(&project.join("b_big.log"), 5000, 2 * 24 * 60 * minute);
+    write_file(&project.join("c_mid.log"), 1000, 60 * minute);
+    fs::create_dir(project.join("sub")).unwrap();
+    write_file(&project.join("sub/x.txt"), 1, 10 * minute);
+    write_file(&project.join("sub/y.txt"), 100, 5 * minute);
+
+    harness.editor_mut().focus_file_explorer();
+    harness.wait_for_file_explorer().unwrap();
+    harness.wait_for_file_explorer_item("sub").unwrap();
+    harness.send_key(KeyCode::Down, KeyModifiers::NONE).unwrap();
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    harness.wait_for_file_explorer_item("x.txt").unwrap();
+
+    let names = [
+        "sub",
+        "x.txt",
+        "y.txt",
+        "a_small.log",
+        "b_big.log",
+        "c_mid.log",
+    ];
+    assert_eq!(
+        shown_order(&harness, &names),
+        [
+            "sub",
+            "x.txt",
+            "y.txt",
+            "a_small.log",
+            "b_big.log",
+            "c_mid.log"
+        ]
+    );
+    harness.assert_screen_not_contains("Name ▲");
+
+    let steps = [
+        (
+            "Sorted by name (Z-A)",
+            "Name ▼",
+            [
+                "sub",
+                "y.txt",
+                "x.txt",
+                "c_mid.log",
+                "b_big.log",
+                "a_small.log",
+            ],
+        ),
+        (
+            "Sorted by modified time (newest first)",
+            "Modified ▼",
+            [
+                "sub",
+                "y.txt",
+                "x.txt",
+                "a_small.log",
+                "c_mid.log",
+                "b_big.log",
+            ],
+        ),
+        (
+            "Sorted by modified time (oldest first)",
+            "Modified ▲",
+            [
+                "sub",
+                "x.txt",
+                "y.txt",
+                "b_big.log",
+                "c_mid.log",
+                "a_small.log",
+            ],
+        ),
+        (
+            "Sorted by size (largest first)",
+            "Size ▼",
+            [
+                "sub",
+                "y.txt",
+                "x.txt",
+                "b_big.log",
+                "c_mid.log",
+                "a_small.log",
+            ],
+        ),
+        (
+            "Sorted by size (smallest first)",
+            "Size ▲",
+            [
+                "sub",
+                "x.txt",
+                "y.txt",
+                "a_small.log",
+                "c_mid.log",
+                "b_big.log",
+            ],
+        ),
+    ];
+    for (status, header, order) in steps {
+        harness
+            .send_key(KeyCode::Char('s'), KeyModifiers::NONE)
+            .unwrap();
+        assert_eq!(
+            harness.editor().get_status_message().map(String::as_str),
+            Some(status)
+        );
+        // Entries of `sub` were listed without metadata, which comes later
+        harness
+            .wait_until(|h| shown_order(h, &names) == order)
+            .unwrap();
+        harness.assert_screen_contains(header);
+    }
+
+    // Back to the default sort, the header row is gone
+    harness
+        .send_key(KeyCode::Char('s'), KeyModifiers::NONE)
+        .unwrap();
+    assert_eq!(
+        shown_order(&harness, &names),
+        [
+            "sub",
+            "x.txt",
+            "y.txt",
+            "a_small.log",
+            "b_big.log",
+            "c_mid.log"
+        ]
+    );
+    harness.assert_screen_not_contains("Name ▲");
+}
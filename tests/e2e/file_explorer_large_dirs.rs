@@ -0,0 +1,203 @@
+//! Tests for expanding very large directories in the file explorer
+
+use crate::common::harness::{EditorTestHarness, HarnessOptions};
+use crossterm::event::{KeyCode, KeyModifiers};
+use fresh::config::Config;
+use fresh::services::fs::SlowFsConfig;
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+fn create_files(dir: &Path, count: usize) {
+    fs::create_dir(dir).unwrap();
+    for i in 0..count {
+        fs::write(dir.join(format!("file{:04}.txt", i)), "x").unwrap();
+    }
+}
+
+/// Open the explorer and select the root's first entry
+fn open_explorer_on_first_entry(harness: &mut EditorTestHarness, name: &str) {
+    harness.editor_mut().focus_file_explorer();
+    harness.wait_for_file_explorer().unwrap();
+    harness.wait_for_file_explorer_item(name).unwrap();
+    harness.send_key(KeyCode::Down, KeyModifiers::NONE).unwrap();
+}
+
+fn selected_dir_children(harness: &EditorTestHarness) -> usize {
+    let explorer = harness.editor().file_explorer().unwrap();
+    let selected = explorer.get_selected().unwrap();
+    explorer.tree().get_node(selected).unwrap().children.len()
+}
+
+#[test]
+fn test_huge_directory_paints_before_listing_completes() {
+    // Every stat takes 200µs; the listing is handed over one batch of 500
+    // entries per main loop pass
+    let slow_fs = SlowFsConfig {
+        metadata_delay: Duration::from_micros(200),
+        ..SlowFsConfig::none()
+    };
+    let mut harness = EditorTestHarness::create(
+        100,
+        30,
+        HarnessOptions::new()
+            .with_project_root()
+            .with_slow_fs(slow_fs),
+    )
+    .unwrap();
+    create_files(&harness.project_dir().unwrap().join("big"), 5000);
+
+    open_explorer_on_first_entry(&mut harness, "big");
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+
+    // The first batch is on screen while the rest is still being read
+    let explorer = harness.editor().file_explorer().unwrap();
+    let big = explorer.get_selected().unwrap();
+    assert!(explorer.tree().get_node(big).unwrap().is_loading());
+    let shown = selected_dir_children(&harness);
+    assert!(shown > 0 && shown < 5000, "shown {shown} entries");
+    let first = explorer.tree().visible_children(big)[0];
+    let first_name = explorer.tree().get_node(first).unwrap().entry.name.clone();
+    harness.assert_screen_contains(&first_name);
+    harness.assert_screen_contains("Loading big...");
+
+    harness
+        .wait_until(|h| {
+            let explorer = h.editor().file_explorer().unwrap();
+            explorer.tree().get_node(big).unwrap().is_expanded()
+        })
+        .unwrap();
+
+    assert_eq!(selected_dir_children(&harness), 5000);
+    // Later batches didn't move the selection
+    assert_eq!(
+        harness.editor().file_explorer().unwrap().get_selected(),
+        Some(big)
+    );
+    harness.assert_screen_not_contains("(loading…)");
+    harness.assert_screen_contains("file0000.txt");
+}
+
+#[test]
+fn test_huge_directory_stats_only_entries_on_screen() {
+    // Every stat takes 200µs, so stat'ing the whole directory would take
+    // about a second
+    let slow_fs = SlowFsConfig {
+        metadata_delay: Duration::from_micros(200),
+        ..SlowFsConfig::none()
+    };
+    let mut harness = EditorTestHarness::create(
+        100,
+        30,
+        HarnessOptions::new()
+            .with_project_root()
+            .with_slow_fs(slow_fs),
+    )
+    .unwrap();
+    create_files(&harness.project_dir().unwrap().join("big"), 5000);
+
+    open_explorer_on_first_entry(&mut harness, "big");
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    let big = harness
+        .editor()
+        .file_explorer()
+        .unwrap()
+        .get_selected()
+        .unwrap();
+    harness
+        .wait_until(|h| {
+            let explorer = h.editor().file_explorer().unwrap();
+            explorer.tree().get_node(big).unwrap().is_expanded()
+        })
+        .unwrap();
+    assert_eq!(selected_dir_children(&harness), 5000);
+    harness.assert_screen_contains("file0000.txt");
+
+    // Sizes show up for the entries on screen, and only those were stat'ed
+    harness.wait_for_screen_contains("0.0 KB").unwrap();
+    let explorer = harness.editor().file_explorer().unwrap();
+    let tree = explorer.tree();
+    let stated = tree
+        .get_node(big)
+        .unwrap()
+        .children
+        .iter()
+        .filter(|&&id| tree.get_node(id).unwrap().entry.metadata.is_some())
+        .count();
+    assert!(stated > 0 && stated <= 30, "stat'ed {stated} entries");
+    // Later batches didn't move the selection
+    assert_eq!(explorer.get_selected(), Some(big));
+}
+
+#[test]
+fn test_entries_past_cap_are_hidden_until_loaded() {
+    let mut config = Config::default();
+    config.file_explorer.max_visible_entries = 50;
+    let mut harness = EditorTestHarness::create(
+        100,
+        30,
+        HarnessOptions::new()
+            .with_project_root()
+            .with_config(config),
+    )
+    .unwrap();
+    create_files(&harness.project_dir().unwrap().join("big"), 120);
+
+    open_explorer_on_first_entry(&mut harness, "big");
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    harness.wait_for_file_explorer_item("file0000.txt").unwrap();
+
+    let explorer = harness.editor().file_explorer().unwrap();
+    let big = explorer.get_selected().unwrap();
+    assert_eq!(explorer.tree().visible_children(big).len(), 50);
+
+    // The footer row is scrolled into view with the last shown entry
+    harness
+        .send_key_repeat(KeyCode::PageDown, KeyModifiers::NONE, 3)
+        .unwrap();
+    // The explorer is too narrow for the whole hint
+    harness.assert_screen_contains("… 70 more not shown, p");
+
+    harness
+        .send_key(KeyCode::Char('l'), KeyModifiers::SHIFT)
+        .unwrap();
+    let explorer = harness.editor().file_explorer().unwrap();
+    assert_eq!(explorer.tree().visible_children(big).len(), 120);
+    assert_eq!(explorer.tree().hidden_child_count(big), 0);
+    harness.assert_screen_not_contains("more not shown");
+}
+
+#[test]
+fn test_collapsed_directory_entry_count_badge() {
+    let mut config = Config::default();
+    config.file_explorer.show_entry_counts = true;
+    config.editor.file_tree_poll_interval_ms = 0;
+    let mut harness = EditorTestHarness::create(
+        100,
+        30,
+        HarnessOptions::new()
+            .with_project_root()
+            .with_config(config),
+    )
+    .unwrap();
+    let sub = harness.project_dir().unwrap().join("sub");
+    create_files(&sub, 3);
+
+    harness.editor_mut().focus_file_explorer();
+    harness.wait_for_file_explorer().unwrap();
+    harness
+        .wait_until(|h| h.screen_to_string().contains("3 items"))
+        .unwrap();
+
+    // A new file changes the directory, which invalidates the cached count
+    fs::write(sub.join("another.txt"), "x").unwrap();
+    harness
+        .wait_until(|h| h.screen_to_string().contains("4 items"))
+        .unwrap();
+}
@@ -0,0 +1,247 @@
+//! Tests for opening files whose contents arrive slowly (network mounts)
+
+use crate::common::harness::EditorTestHarness;
+use crossterm::event::{KeyCode, KeyModifiers};
+use fresh::config::Config;
+use fresh::config_io::DirectoryContext;
+use fresh::services::file_loader::FileSource;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tempfile::TempDir;
+
+/// Reads the real file, but only once the test opens the gate, optionally
+/// failing after `fail_after` bytes like a dropped network connection
+struct GatedSource {
+    open: Arc<AtomicBool>,
+    fail_after: Option<usize>,
+}
+
+struct GatedReader {
+    inner: std::fs::File,
+    open: Arc<AtomicBool>,
+    fail_after: Option<usize>,
+    read: usize,
+}
+
+impl FileSource for GatedSource {
+    fn size(&self, path: &Path) -> io::Result<u64> {
+        Ok(std::fs::metadata(path)?.len())
+    }
+
+    fn open(&self, path: &Path) -> io::Result<Box<dyn Read + Send>> {
+        Ok(Box::new(GatedReader {
+            inner: std::fs::File::open(path)?,
+            open: Arc::clone(&self.open),
+            fail_after: self.fail_after,
+            read: 0,
+        }))
+    }
+}
+
+impl Read for GatedReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while !self.open.load(Ordering::SeqCst) {
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        let limit = match self.fail_after {
+            Some(limit) if self.read >= limit => {
+                return Err(io::Error::other("connection reset"));
+            }
+            Some(limit) => (limit - self.read).min(buf.len()),
+            None => buf.len(),
+        };
+        let n = self.inner.read(&mut buf[..limit])?;
+        self.read += n;
+        Ok(n)
+    }
+}
+
+/// Harness whose file reads wait for the returned gate, so opening a file
+/// immediately falls back to the loading placeholder
+fn slow_harness(
+    content: &str,
+    fail_after: Option<usize>,
+) -> (EditorTestHarness, Arc<AtomicBool>, PathBuf, TempDir) {
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join("remote.txt");
+    std::fs::write(&path, content).unwrap();
+
+    let mut harness = EditorTestHarness::new(80, 24).unwrap();
+    let gate = gate_file_source(&mut harness, fail_after);
+    (harness, gate, path, temp_dir)
+}
+
+/// Route the harness's file reads through a closed gate; returns the gate
+fn gate_file_source(harness: &mut EditorTestHarness, fail_after: Option<usize>) -> Arc<AtomicBool> {
+    let gate = Arc::new(AtomicBool::new(false));
+    harness.editor_mut().set_file_source(Arc::new(GatedSource {
+        open: Arc::clone(&gate),
+        fail_after,
+    }));
+    harness
+        .editor_mut()
+        .set_quick_load_timeout(Duration::from_millis(1));
+    gate
+}
+
+#[test]
+fn test_slow_file_shows_placeholder_until_loaded() {
+    let (mut harness, gate, path, _temp_dir) = slow_harness("remote contents\n", None);
+
+    harness.open_file(&path).unwrap();
+    let buffer_id = harness.editor().active_buffer();
+    assert!(harness.editor().is_buffer_loading(buffer_id));
+    harness.assert_screen_contains("Loading remote.txt");
+    harness.assert_screen_not_contains("remote contents");
+
+    gate.store(true, Ordering::SeqCst);
+    harness
+        .wait_until(|h| !h.editor().is_buffer_loading(buffer_id))
+        .unwrap();
+
+    harness.assert_screen_contains("remote contents");
+    harness.assert_screen_not_contains("Loading remote.txt");
+    assert!(!harness.editor().is_editing_disabled());
+    assert_eq!(
+        harness.editor().get_status_message().unwrap(),
+        "Opened remote.txt"
+    );
+}
+
+#[test]
+fn test_typing_during_load_is_applied_after_load() {
+    let (mut harness, gate, path, _temp_dir) = slow_harness("world\n", None);
+
+    harness.open_file(&path).unwrap();
+    let buffer_id = harness.editor().active_buffer();
+    harness.type_text("hello ").unwrap();
+    assert!(!harness.get_buffer_content().unwrap().contains("hello"));
+
+    gate.store(true, Ordering::SeqCst);
+    harness
+        .wait_until(|h| !h.editor().is_buffer_loading(buffer_id))
+        .unwrap();
+
+    assert_eq!(harness.get_buffer_content().unwrap(), "hello world\n");
+}
+
+#[test]
+fn test_save_is_blocked_while_loading() {
+    let (mut harness, gate, path, _temp_dir) = slow_harness("original\n", None);
+
+    harness.open_file(&path).unwrap();
+    let buffer_id = harness.editor().active_buffer();
+    harness
+        .send_key(KeyCode::Char('s'), KeyModifiers::CONTROL)
+        .unwrap();
+    harness.render().unwrap();
+
+    assert!(harness
+        .editor()
+        .get_status_message()
+        .unwrap()
+        .contains("still loading"));
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "original\n");
+
+    gate.store(true, Ordering::SeqCst);
+    harness
+        .wait_until(|h| !h.editor().is_buffer_loading(buffer_id))
+        .unwrap();
+}
+
+#[test]
+fn test_read_error_leaves_errored_buffer() {
+    let content = "x".repeat(1024);
+    let (mut harness, gate, path, _temp_dir) = slow_harness(&content, Some(16));
+
+    harness.open_file(&path).unwrap();
+    let buffer_id = harness.editor().active_buffer();
+    gate.store(true, Ordering::SeqCst);
+    harness
+        .wait_until(|h| h.editor().buffer_load_failed(buffer_id))
+        .unwrap();
+
+    harness.assert_screen_contains("Failed to load remote.txt: connection reset");
+    assert!(harness.editor().is_editing_disabled());
+
+    // Neither editing nor saving may clobber the file with the placeholder
+    harness.type_text("oops").unwrap();
+    harness
+        .send_key(KeyCode::Char('s'), KeyModifiers::CONTROL)
+        .unwrap();
+    harness.render().unwrap();
+    assert!(!harness.get_buffer_content().unwrap().contains("oops"));
+    assert_eq!(
+        harness.editor().get_status_message().unwrap(),
+        "Failed to load file: connection reset"
+    );
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), content);
+}
+
+#[test]
+fn test_fast_file_opens_without_placeholder() {
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join("local.txt");
+    std::fs::write(&path, "local contents\n").unwrap();
+
+    let mut harness = EditorTestHarness::new(80, 24).unwrap();
+    harness.open_file(&path).unwrap();
+
+    assert!(!harness
+        .editor()
+        .is_buffer_loading(harness.editor().active_buffer()));
+    harness.assert_screen_contains("local contents");
+}
+
+#[test]
+fn test_session_restore_applies_cursor_after_load() {
+    let temp_dir = TempDir::new().unwrap();
+    let dir_context = DirectoryContext::for_testing(&temp_dir.path().join("dirs"));
+    let project_dir = temp_dir.path().join("project");
+    std::fs::create_dir(&project_dir).unwrap();
+    let file = project_dir.join("remote.txt");
+    std::fs::write(&file, "Line 1\nLine 2\nLine 3\nLine 4\n").unwrap();
+
+    let cursor_before;
+    {
+        let mut harness = EditorTestHarness::with_shared_dir_context(
+            80,
+            24,
+            Config::default(),
+            project_dir.clone(),
+            dir_context.clone(),
+        )
+        .unwrap();
+        harness.open_file(&file).unwrap();
+        harness
+            .send_key_repeat(KeyCode::Down, KeyModifiers::NONE, 2)
+            .unwrap();
+        cursor_before = harness.cursor_position();
+        assert!(cursor_before > 0);
+        harness.editor_mut().save_session().unwrap();
+    }
+
+    let mut harness = EditorTestHarness::with_shared_dir_context(
+        80,
+        24,
+        Config::default(),
+        project_dir.clone(),
+        dir_context.clone(),
+    )
+    .unwrap();
+    let gate = gate_file_source(&mut harness, None);
+    assert!(harness.editor_mut().try_restore_session().unwrap());
+    let buffer_id = harness.editor().active_buffer();
+    assert!(harness.editor().is_buffer_loading(buffer_id));
+
+    gate.store(true, Ordering::SeqCst);
+    harness
+        .wait_until(|h| !h.editor().is_buffer_loading(buffer_id))
+        .unwrap();
+
+    harness.assert_buffer_content("Line 1\nLine 2\nLine 3\nLine 4\n");
+    assert_eq!(harness.cursor_position(), cursor_before);
+}
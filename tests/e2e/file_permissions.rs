@@ -1,4 +1,5 @@
-// End-to-end tests for file permission preservation during save
+// End-to-end tests for file permissions: preserving them on save, and
+// files that can't be written
 
 use crate::common::harness::EditorTestHarness;
 use crossterm::event::{KeyCode, KeyModifiers};
@@ -261,3 +262,166 @@ fn test_save_preserves_various_permission_modes() {
         );
     }
 }
+
+/// Take write permission away from `path`, returning false if it can still
+/// be written (as when running as root), in which case the test is skipped
+#[cfg(unix)]
+fn make_read_only(path: &std::path::Path, mode: u32) -> bool {
+    std::fs::set_permissions(path, Permissions::from_mode(mode)).unwrap();
+    let writable = if path.is_dir() {
+        std::fs::write(path.join(".probe"), "").is_ok()
+    } else {
+        std::fs::OpenOptions::new().write(true).open(path).is_ok()
+    };
+    if writable {
+        eprintln!("Skipping: {} is still writable", path.display());
+    }
+    !writable
+}
+
+/// Number of notifications so far that say a file is read-only
+#[cfg(unix)]
+fn read_only_notices(harness: &EditorTestHarness) -> usize {
+    harness
+        .editor()
+        .notifications()
+        .history()
+        .filter(|n| n.message.contains("is read-only"))
+        .count()
+}
+
+/// A file without write permission opens read-only: the status bar marks it,
+/// a notification says why once, and edits are rejected
+#[test]
+#[cfg(unix)]
+fn test_read_only_file_is_marked_and_rejects_edits() {
+    let temp_dir = TempDir::new().unwrap();
+    let file_path = temp_dir.path().join("locked.txt");
+    std::fs::write(&file_path, "locked content\n").unwrap();
+    if !make_read_only(&file_path, 0o444) {
+        return;
+    }
+
+    let mut harness = EditorTestHarness::new(80, 24).unwrap();
+    harness.open_file(&file_path).unwrap();
+    harness.render().unwrap();
+    harness.assert_screen_contains("locked.txt [RO]");
+    assert!(harness
+        .editor()
+        .notifications()
+        .history()
+        .any(|n| n.message.contains("don't have permission")));
+
+    harness.type_text("x").unwrap();
+    harness.render().unwrap();
+    assert_eq!(harness.get_buffer_content().unwrap(), "locked content\n");
+    assert_eq!(
+        harness.editor().get_status_message().unwrap(),
+        "Editing disabled in this buffer"
+    );
+
+    // Checking again before saving doesn't repeat the notification
+    harness
+        .send_key(KeyCode::Char('s'), KeyModifiers::CONTROL)
+        .unwrap();
+    harness.render().unwrap();
+    assert_eq!(read_only_notices(&harness), 1);
+
+    // Once the file can be written the buffer unlocks
+    std::fs::set_permissions(&file_path, Permissions::from_mode(0o644)).unwrap();
+    harness
+        .editor_mut()
+        .handle_file_changed(file_path.to_str().unwrap());
+    harness.render().unwrap();
+    harness.assert_screen_not_contains("[RO]");
+    harness.type_text("x").unwrap();
+    assert_eq!(harness.get_buffer_content().unwrap(), "xlocked content\n");
+}
+
+/// "Edit Anyway" unlocks a read-only file; saving it then fails and offers
+/// "Save As…", which writes the edits elsewhere
+#[test]
+#[cfg(unix)]
+fn test_edit_anyway_then_save_as_fallback() {
+    let temp_dir = TempDir::new().unwrap();
+    let locked_dir = temp_dir.path().join("locked");
+    std::fs::create_dir(&locked_dir).unwrap();
+    let file_path = locked_dir.join("config.txt");
+    std::fs::write(&file_path, "setting = 1\n").unwrap();
+    std::fs::set_permissions(&file_path, Permissions::from_mode(0o444)).unwrap();
+    if !make_read_only(&locked_dir, 0o555) {
+        std::fs::set_permissions(&locked_dir, Permissions::from_mode(0o755)).unwrap();
+        return;
+    }
+
+    let mut harness = EditorTestHarness::new(80, 24).unwrap();
+    harness.open_file(&file_path).unwrap();
+    harness.render().unwrap();
+    harness.assert_screen_contains("[RO]");
+
+    harness
+        .send_key(KeyCode::Char('p'), KeyModifiers::CONTROL)
+        .unwrap();
+    harness.type_text("Edit Anyway").unwrap();
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    harness.render().unwrap();
+    harness.assert_screen_not_contains("[RO]");
+    assert!(harness
+        .editor()
+        .get_status_message()
+        .unwrap()
+        .contains("saving it will need elevated rights"));
+
+    harness.type_text("# ").unwrap();
+    harness
+        .send_key(KeyCode::Char('s'), KeyModifiers::CONTROL)
+        .unwrap();
+    harness.render().unwrap();
+    assert_eq!(
+        std::fs::read_to_string(&file_path).unwrap(),
+        "setting = 1\n"
+    );
+    assert!(harness
+        .editor()
+        .get_status_message()
+        .unwrap()
+        .starts_with("Failed to save"));
+    harness.assert_screen_contains("Save As…");
+    // No sudo command is configured
+    harness.assert_screen_not_contains("Retry with sudo");
+
+    // Focus starts on "Retry"; Tab moves on to "Save As…"
+    harness.editor_mut().focus_notifications();
+    harness.send_key(KeyCode::Tab, KeyModifiers::NONE).unwrap();
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    harness.wait_for_screen_contains("Save as:").unwrap();
+    harness
+        .send_key(KeyCode::Char('a'), KeyModifiers::CONTROL)
+        .unwrap();
+    let copy_path = temp_dir.path().join("config.txt");
+    harness.type_text(copy_path.to_str().unwrap()).unwrap();
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    harness.render().unwrap();
+
+    assert_eq!(
+        std::fs::read_to_string(&copy_path).unwrap(),
+        "# setting = 1\n"
+    );
+    assert_eq!(
+        std::fs::read_to_string(&file_path).unwrap(),
+        "setting = 1\n"
+    );
+    harness.type_text("more ").unwrap();
+    assert_eq!(
+        harness.get_buffer_content().unwrap(),
+        "# more setting = 1\n"
+    );
+
+    std::fs::set_permissions(&locked_dir, Permissions::from_mode(0o755)).unwrap();
+}
@@ -0,0 +1,313 @@
+//! Tests for saves that take long enough to finish in the background
+
+use crate::common::harness::EditorTestHarness;
+use crossterm::event::{KeyCode, KeyModifiers};
+use fresh::services::file_saver::{FileSink, LocalFileSink};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tempfile::TempDir;
+
+/// Writes the real file, but only once the test opens the gate, failing
+/// instead of completing while `fail` is set
+struct GatedSink {
+    open: Arc<AtomicBool>,
+    fail: Arc<AtomicBool>,
+}
+
+struct GatedWriter<'a> {
+    inner: &'a mut dyn Write,
+    open: &'a AtomicBool,
+}
+
+impl FileSink for GatedSink {
+    fn replace(
+        &self,
+        path: &Path,
+        contents: &mut dyn FnMut(&mut dyn Write) -> io::Result<()>,
+    ) -> io::Result<u64> {
+        LocalFileSink.replace(path, &mut |out| {
+            contents(&mut GatedWriter {
+                inner: out,
+                open: &self.open,
+            })?;
+            if self.fail.load(Ordering::SeqCst) {
+                return Err(io::Error::other("disk full"));
+            }
+            Ok(())
+        })
+    }
+}
+
+impl Write for GatedWriter<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        while !self.open.load(Ordering::SeqCst) {
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        self.inner.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+struct SlowSave {
+    harness: EditorTestHarness,
+    gate: Arc<AtomicBool>,
+    fail: Arc<AtomicBool>,
+    path: PathBuf,
+    /// Bind it when destructuring: left to `..`, it is dropped right away
+    /// and deletes the file
+    _temp_dir: TempDir,
+}
+
+/// Harness with `content` open whose file writes wait for the gate, so
+/// saving immediately continues in the background
+fn slow_save(content: &str) -> SlowSave {
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join("big.txt");
+    std::fs::write(&path, content).unwrap();
+
+    let mut harness = EditorTestHarness::new(80, 24).unwrap();
+    harness.open_file(&path).unwrap();
+    let gate = Arc::new(AtomicBool::new(false));
+    let fail = Arc::new(AtomicBool::new(false));
+    harness.editor_mut().set_file_sink(Arc::new(GatedSink {
+        open: Arc::clone(&gate),
+        fail: Arc::clone(&fail),
+    }));
+    harness
+        .editor_mut()
+        .set_quick_save_timeout(Duration::from_millis(1));
+    SlowSave {
+        harness,
+        gate,
+        fail,
+        path,
+        _temp_dir: temp_dir,
+    }
+}
+
+fn save(harness: &mut EditorTestHarness) {
+    harness
+        .send_key(KeyCode::Char('s'), KeyModifiers::CONTROL)
+        .unwrap();
+}
+
+fn is_modified(harness: &EditorTestHarness) -> bool {
+    harness.editor().active_state().buffer.is_modified()
+}
+
+#[test]
+fn test_editing_continues_during_slow_save() {
+    let SlowSave {
+        mut harness,
+        gate,
+        path,
+        _temp_dir,
+        ..
+    } = slow_save("world\n");
+    let buffer_id = harness.editor().active_buffer();
+
+    harness.type_text("hello ").unwrap();
+    save(&mut harness);
+    assert!(harness.editor().is_buffer_saving(buffer_id));
+    let progress = harness.editor().progress().entries();
+    assert_eq!(progress.len(), 1);
+    assert_eq!(progress[0].title, "Saving big.txt");
+    harness.render().unwrap();
+    assert!(harness.get_status_bar().contains("Saving big.txt"));
+
+    // Input is handled while the file is being written
+    harness.type_text("there ").unwrap();
+    assert_eq!(harness.get_buffer_content().unwrap(), "hello there world\n");
+
+    gate.store(true, Ordering::SeqCst);
+    harness
+        .wait_until(|h| !h.editor().is_buffer_saving(buffer_id))
+        .unwrap();
+
+    // The file has the contents from when saving started, and the edits
+    // made since keep the buffer modified
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "hello world\n");
+    assert!(is_modified(&harness));
+    assert_eq!(
+        harness.editor().get_status_message().unwrap(),
+        "Saved big.txt"
+    );
+
+    save(&mut harness);
+    harness
+        .wait_until(|h| !h.editor().is_buffer_saving(buffer_id))
+        .unwrap();
+    assert_eq!(
+        std::fs::read_to_string(&path).unwrap(),
+        "hello there world\n"
+    );
+    assert!(!is_modified(&harness));
+}
+
+#[test]
+fn test_modified_flag_cleared_only_when_save_completes() {
+    let SlowSave {
+        mut harness,
+        gate,
+        path,
+        _temp_dir,
+        ..
+    } = slow_save("world\n");
+    let buffer_id = harness.editor().active_buffer();
+
+    harness.type_text("hello ").unwrap();
+    save(&mut harness);
+    harness.process_async_and_render().unwrap();
+    assert!(is_modified(&harness));
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "world\n");
+
+    gate.store(true, Ordering::SeqCst);
+    harness
+        .wait_until(|h| !h.editor().is_buffer_saving(buffer_id))
+        .unwrap();
+    assert!(!is_modified(&harness));
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "hello world\n");
+}
+
+#[test]
+fn test_save_during_save_writes_newest_contents() {
+    let SlowSave {
+        mut harness,
+        gate,
+        path,
+        _temp_dir,
+        ..
+    } = slow_save("world\n");
+    let buffer_id = harness.editor().active_buffer();
+
+    harness.type_text("hello ").unwrap();
+    save(&mut harness);
+    harness.type_text("there ").unwrap();
+    save(&mut harness);
+    assert!(harness
+        .editor()
+        .get_status_message()
+        .unwrap()
+        .contains("once the running save finishes"));
+
+    gate.store(true, Ordering::SeqCst);
+    harness
+        .wait_until(|h| !h.editor().is_buffer_saving(buffer_id))
+        .unwrap();
+
+    assert_eq!(
+        std::fs::read_to_string(&path).unwrap(),
+        "hello there world\n"
+    );
+    assert!(!is_modified(&harness));
+}
+
+#[test]
+fn test_failed_save_offers_retry() {
+    let SlowSave {
+        mut harness,
+        gate,
+        fail,
+        path,
+        _temp_dir,
+        ..
+    } = slow_save("world\n");
+    let buffer_id = harness.editor().active_buffer();
+
+    harness.type_text("hello ").unwrap();
+    fail.store(true, Ordering::SeqCst);
+    save(&mut harness);
+    gate.store(true, Ordering::SeqCst);
+    harness
+        .wait_until(|h| !h.editor().is_buffer_saving(buffer_id))
+        .unwrap();
+
+    // The file and the modified flag are untouched
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "world\n");
+    assert!(is_modified(&harness));
+    assert_eq!(
+        harness.editor().get_status_message().unwrap(),
+        "Failed to save big.txt: disk full"
+    );
+    harness.assert_screen_contains("Retry");
+
+    fail.store(false, Ordering::SeqCst);
+    harness.editor_mut().focus_notifications();
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    harness
+        .wait_until(|h| !h.editor().is_buffer_saving(buffer_id) && !is_modified(h))
+        .unwrap();
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "hello world\n");
+}
+
+#[test]
+fn test_quit_waits_for_running_save() {
+    let SlowSave {
+        mut harness,
+        gate,
+        path,
+        _temp_dir,
+        ..
+    } = slow_save("world\n");
+
+    harness.type_text("hello ").unwrap();
+    save(&mut harness);
+    harness
+        .send_key(KeyCode::Char('q'), KeyModifiers::CONTROL)
+        .unwrap();
+    harness.process_async_and_render().unwrap();
+    assert!(!harness.editor().should_quit());
+    assert!(harness
+        .editor()
+        .get_status_message()
+        .unwrap()
+        .starts_with("Waiting for 1 save(s) to finish before quitting"));
+
+    gate.store(true, Ordering::SeqCst);
+    harness.wait_until(|h| h.editor().should_quit()).unwrap();
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "hello world\n");
+}
+
+#[test]
+fn test_quit_asks_when_save_takes_too_long() {
+    let SlowSave {
+        mut harness,
+        gate,
+        _temp_dir,
+        ..
+    } = slow_save("world\n");
+
+    harness.type_text("hello ").unwrap();
+    save(&mut harness);
+    harness
+        .send_key(KeyCode::Char('q'), KeyModifiers::CONTROL)
+        .unwrap();
+    harness.advance_time(Duration::from_secs(31));
+    harness.process_async_and_render().unwrap();
+    harness.assert_screen_contains("1 file(s) still saving");
+
+    // Cancelling keeps the editor open and the save running
+    harness
+        .send_key(KeyCode::Char('c'), KeyModifiers::NONE)
+        .unwrap();
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    assert!(!harness.editor().should_quit());
+    let buffer_id = harness.editor().active_buffer();
+    assert!(harness.editor().is_buffer_saving(buffer_id));
+
+    gate.store(true, Ordering::SeqCst);
+    harness
+        .wait_until(|h| !h.editor().is_buffer_saving(buffer_id))
+        .unwrap();
+    assert!(!harness.editor().should_quit());
+}
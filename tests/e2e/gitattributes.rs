@@ -0,0 +1,109 @@
+//! E2E tests for `.gitattributes` save conventions
+
+use crate::common::harness::EditorTestHarness;
+use crossterm::event::{KeyCode, KeyModifiers};
+use fresh::model::encoding::TextEncoding;
+use std::fs;
+use tempfile::TempDir;
+
+/// A work tree with the given `.gitattributes`
+fn repo(attributes: &str) -> TempDir {
+    let temp_dir = TempDir::new().unwrap();
+    fs::create_dir(temp_dir.path().join(".git")).unwrap();
+    fs::write(temp_dir.path().join(".gitattributes"), attributes).unwrap();
+    temp_dir
+}
+
+fn save(harness: &mut EditorTestHarness) {
+    harness
+        .send_key(KeyCode::Char('s'), KeyModifiers::CONTROL)
+        .unwrap();
+    harness.render().unwrap();
+}
+
+/// `eol` converts the file on save and says so the first time only
+#[test]
+fn test_eol_attribute_overrides_detected_line_endings() {
+    let temp_dir = repo("* text eol=lf\n*.bat eol=crlf\n");
+    let file_path = temp_dir.path().join("run.bat");
+    fs::write(&file_path, "echo one\necho two\n").unwrap();
+
+    let mut harness = EditorTestHarness::new(160, 24).unwrap();
+    harness.open_file(&file_path).unwrap();
+    harness.type_text("rem ").unwrap();
+    save(&mut harness);
+
+    assert_eq!(
+        fs::read_to_string(&file_path).unwrap(),
+        "rem echo one\r\necho two\r\n"
+    );
+    harness.assert_screen_contains("eol=crlf enforced by .gitattributes");
+
+    harness.type_text("x").unwrap();
+    save(&mut harness);
+    assert_eq!(
+        fs::read_to_string(&file_path).unwrap(),
+        "rem xecho one\r\necho two\r\n"
+    );
+    harness.assert_screen_not_contains("enforced by .gitattributes");
+}
+
+/// Files outside a git work tree keep their own line endings
+#[test]
+fn test_gitattributes_outside_repository_ignored() {
+    let temp_dir = TempDir::new().unwrap();
+    fs::write(temp_dir.path().join(".gitattributes"), "* eol=crlf\n").unwrap();
+    let file_path = temp_dir.path().join("notes.txt");
+    fs::write(&file_path, "one\n").unwrap();
+
+    let mut harness = EditorTestHarness::new(160, 24).unwrap();
+    harness.open_file(&file_path).unwrap();
+    harness.type_text("x").unwrap();
+    save(&mut harness);
+
+    assert_eq!(fs::read_to_string(&file_path).unwrap(), "xone\n");
+}
+
+/// `working-tree-encoding` files are decoded on open and encoded on save
+#[test]
+fn test_working_tree_encoding_round_trips() {
+    let temp_dir = repo("*.ps1 text working-tree-encoding=UTF-16LE\n");
+    let file_path = temp_dir.path().join("script.ps1");
+    let encoding = TextEncoding::from_name("UTF-16LE").unwrap();
+    fs::write(
+        &file_path,
+        encoding.encode("Write-Host 'héllo'\n".as_bytes()),
+    )
+    .unwrap();
+
+    let mut harness = EditorTestHarness::new(160, 24).unwrap();
+    harness.open_file(&file_path).unwrap();
+    harness.assert_screen_contains("Write-Host 'héllo'");
+    harness.type_text("# ").unwrap();
+    save(&mut harness);
+
+    assert_eq!(
+        fs::read(&file_path).unwrap(),
+        encoding.encode("# Write-Host 'héllo'\n".as_bytes())
+    );
+    // The file was already in the requested encoding, so nothing was enforced
+    harness.assert_screen_not_contains("enforced by .gitattributes");
+}
+
+/// A file opened as UTF-8 is converted to the requested encoding
+#[test]
+fn test_working_tree_encoding_enforced_on_utf8_file() {
+    let temp_dir = repo("*.txt working-tree-encoding=UTF-16BE\n");
+    let file_path = temp_dir.path().join("a.txt");
+    fs::write(&file_path, "plain\n").unwrap();
+
+    let mut harness = EditorTestHarness::new(160, 24).unwrap();
+    harness.open_file(&file_path).unwrap();
+    harness.assert_screen_contains("plain");
+    harness.type_text("x").unwrap();
+    save(&mut harness);
+
+    let encoding = TextEncoding::from_name("UTF-16BE").unwrap();
+    assert_eq!(fs::read(&file_path).unwrap(), encoding.encode(b"xplain\n"));
+    harness.assert_screen_contains("working-tree-encoding=UTF-16BE enforced");
+}
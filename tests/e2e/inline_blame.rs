@@ -0,0 +1,60 @@
+use crate::common::git_test_helper::GitTestRepo;
+use crate::common::harness::EditorTestHarness;
+use crossterm::event::{KeyCode, KeyModifiers};
+use fresh::config::Config;
+
+/// The cursor line is annotated with its commit, and an edited line with
+/// "Not committed yet"
+#[test]
+#[cfg_attr(target_os = "windows", ignore)]
+fn test_inline_blame_cursor_line() {
+    let repo = GitTestRepo::new();
+    let file = repo.create_file("notes.txt", "first line\nsecond line\n");
+    repo.git_add_all();
+    repo.git_commit("Add notes");
+
+    let mut harness = EditorTestHarness::with_config_and_working_dir(
+        100,
+        20,
+        Config::default(),
+        repo.path.clone(),
+    )
+    .unwrap();
+    harness.open_file(&file).unwrap();
+    harness.render().unwrap();
+
+    harness.editor_mut().toggle_inline_blame(false);
+    harness
+        .wait_until(|h| h.screen_to_string().contains("Add notes"))
+        .unwrap();
+    harness.assert_screen_contains("Test User");
+
+    harness.type_text("new ").unwrap();
+    harness.render().unwrap();
+    harness.assert_screen_contains("Not committed yet");
+    harness.assert_screen_not_contains("Add notes");
+
+    // Back on an unchanged line, the commit shows again without rerunning git
+    harness.send_key(KeyCode::Down, KeyModifiers::NONE).unwrap();
+    harness.render().unwrap();
+    harness.assert_screen_contains("Add notes");
+
+    harness.editor_mut().toggle_inline_blame(false);
+    harness.render().unwrap();
+    harness.assert_screen_not_contains("Add notes");
+}
+
+/// Files outside a repository get a status message instead of annotations
+#[test]
+fn test_inline_blame_not_in_git() {
+    let mut harness = EditorTestHarness::with_temp_project(100, 20).unwrap();
+    let file = harness.project_dir().unwrap().join("plain.txt");
+    std::fs::write(&file, "hello\n").unwrap();
+    harness.open_file(&file).unwrap();
+    harness.render().unwrap();
+
+    harness.editor_mut().toggle_inline_blame(false);
+    harness
+        .wait_until(|h| h.screen_to_string().contains("not tracked by git"))
+        .unwrap();
+}
@@ -0,0 +1,213 @@
+//! E2E tests for the input focus stack: which layer gets each key when
+//! several surfaces are open at once
+
+use crate::common::harness::EditorTestHarness;
+use crossterm::event::{KeyCode, KeyModifiers};
+use fresh::input::focus_stack::InputLayer;
+use fresh::input::keybindings::KeyContext;
+use fresh::model::event::{
+    Event, PopupContentData, PopupData, PopupListItemData, PopupPositionData,
+};
+use fresh::services::plugins::api::{ActionPopupAction, PluginCommand};
+use fresh::view::prompt::PromptType;
+
+fn list_popup(title: &str, items: &[&str]) -> PopupData {
+    PopupData {
+        title: Some(title.to_string()),
+        description: None,
+        transient: false,
+        content: PopupContentData::List {
+            items: items
+                .iter()
+                .map(|item| PopupListItemData {
+                    text: item.to_string(),
+                    detail: None,
+                    icon: None,
+                    data: Some(item.to_string()),
+                })
+                .collect(),
+            selected: 0,
+        },
+        position: PopupPositionData::Centered,
+        width: 40,
+        max_height: 10,
+        bordered: true,
+    }
+}
+
+fn popup_visible(harness: &EditorTestHarness) -> bool {
+    harness.editor().active_state().popups.is_visible()
+}
+
+fn last_layer(harness: &EditorTestHarness) -> Option<InputLayer> {
+    harness.editor().last_key_layer().cloned()
+}
+
+fn start_search_prompt(harness: &mut EditorTestHarness) {
+    harness
+        .editor_mut()
+        .start_prompt("Search: ".to_string(), PromptType::Search);
+}
+
+/// A prompt opened over a completion popup takes typing, and Escape closes
+/// the prompt before the completion
+#[test]
+fn test_prompt_above_completion() -> std::io::Result<()> {
+    let mut harness = EditorTestHarness::new(80, 24)?;
+    harness
+        .editor_mut()
+        .active_state_mut()
+        .apply(&Event::ShowPopup {
+            popup: list_popup("Completion", &["alpha", "beta"]),
+        });
+    start_search_prompt(&mut harness);
+    assert_eq!(
+        harness.editor().focus_stack().describe(),
+        "prompt > completion > editor (normal)"
+    );
+
+    harness.type_text("ab")?;
+    assert_eq!(harness.editor().prompt_input(), Some("ab"));
+    assert_eq!(last_layer(&harness), Some(InputLayer::Prompt));
+    assert!(popup_visible(&harness));
+
+    harness.send_key(KeyCode::Esc, KeyModifiers::NONE)?;
+    assert!(!harness.editor().is_prompting());
+    assert!(popup_visible(&harness));
+    assert_eq!(last_layer(&harness), Some(InputLayer::Prompt));
+
+    harness.send_key(KeyCode::Esc, KeyModifiers::NONE)?;
+    assert!(!popup_visible(&harness));
+    assert_eq!(last_layer(&harness), Some(InputLayer::Completion));
+    Ok(())
+}
+
+/// A plugin's action popup shown while a prompt is active gets Down and
+/// Enter, and Escape closes it before the prompt
+#[test]
+fn test_action_popup_above_prompt() -> std::io::Result<()> {
+    let mut harness = EditorTestHarness::new(80, 24)?;
+    start_search_prompt(&mut harness);
+    harness.type_text("x")?;
+
+    let show_popup = || PluginCommand::ShowActionPopup {
+        popup_id: "choice".to_string(),
+        title: "Pick one".to_string(),
+        message: "Which?".to_string(),
+        actions: vec![
+            ActionPopupAction {
+                id: "first".to_string(),
+                label: "First".to_string(),
+            },
+            ActionPopupAction {
+                id: "second".to_string(),
+                label: "Second".to_string(),
+            },
+        ],
+    };
+    harness
+        .editor_mut()
+        .handle_plugin_command(show_popup())
+        .unwrap();
+    assert_eq!(
+        harness.editor().focus_stack().top(),
+        Some(&InputLayer::Popup)
+    );
+
+    harness.send_key(KeyCode::Down, KeyModifiers::NONE)?;
+    assert_eq!(last_layer(&harness), Some(InputLayer::Popup));
+    harness.send_key(KeyCode::Enter, KeyModifiers::NONE)?;
+    assert_eq!(last_layer(&harness), Some(InputLayer::Popup));
+    assert!(!popup_visible(&harness));
+    assert!(harness.editor().is_prompting());
+    assert_eq!(harness.editor().prompt_input(), Some("x"));
+
+    // Escape closes the popup first, then the prompt
+    harness
+        .editor_mut()
+        .handle_plugin_command(show_popup())
+        .unwrap();
+    harness.send_key(KeyCode::Esc, KeyModifiers::NONE)?;
+    assert!(!popup_visible(&harness));
+    assert!(harness.editor().is_prompting());
+    harness.send_key(KeyCode::Esc, KeyModifiers::NONE)?;
+    assert!(!harness.editor().is_prompting());
+    assert_eq!(last_layer(&harness), Some(InputLayer::Prompt));
+    Ok(())
+}
+
+/// Keys bound by a plugin mode go to a popup while it is open and to the
+/// mode once it closes
+#[test]
+fn test_popup_above_plugin_mode() -> std::io::Result<()> {
+    let mut harness = EditorTestHarness::new(80, 24)?;
+    let editor = harness.editor_mut();
+    editor
+        .handle_plugin_command(PluginCommand::DefineMode {
+            name: "test-mode".to_string(),
+            parent: None,
+            bindings: vec![("j".to_string(), "test_mode_down".to_string())],
+            read_only: false,
+            plugin: None,
+        })
+        .unwrap();
+    editor
+        .handle_plugin_command(PluginCommand::SetEditorMode {
+            mode: Some("test-mode".to_string()),
+        })
+        .unwrap();
+    editor.active_state_mut().apply(&Event::ShowPopup {
+        popup: list_popup("Info", &["one", "two"]),
+    });
+    assert_eq!(
+        harness.editor().focus_stack().describe(),
+        "popup > mode test-mode > editor (normal)"
+    );
+
+    harness.send_key(KeyCode::Char('j'), KeyModifiers::NONE)?;
+    assert_eq!(last_layer(&harness), Some(InputLayer::Popup));
+    assert!(popup_visible(&harness));
+
+    harness.send_key(KeyCode::Esc, KeyModifiers::NONE)?;
+    assert!(!popup_visible(&harness));
+
+    harness.send_key(KeyCode::Char('j'), KeyModifiers::NONE)?;
+    assert_eq!(
+        last_layer(&harness),
+        Some(InputLayer::Mode("test-mode".to_string()))
+    );
+    Ok(())
+}
+
+/// Keys no layer above takes reach the editor's keybindings
+#[test]
+fn test_keys_fall_through_to_editor() -> std::io::Result<()> {
+    let mut harness = EditorTestHarness::new(80, 24)?;
+    harness.type_text("hi")?;
+    assert_eq!(harness.get_buffer_content().unwrap(), "hi");
+    assert_eq!(
+        last_layer(&harness),
+        Some(InputLayer::Editor(KeyContext::Normal))
+    );
+    Ok(())
+}
+
+/// "Show Input Layers" reports the stack and the layer that took the last key
+#[test]
+fn test_show_input_layers() -> std::io::Result<()> {
+    let mut harness = EditorTestHarness::new(80, 24)?;
+    start_search_prompt(&mut harness);
+    harness.type_text("a")?;
+    harness.editor_mut().show_input_layers();
+
+    let message = harness.editor().get_status_message().cloned().unwrap();
+    assert!(
+        message.contains("prompt > editor (normal)"),
+        "unexpected status: {message}"
+    );
+    assert!(
+        message.contains("A → prompt"),
+        "unexpected status: {message}"
+    );
+    Ok(())
+}
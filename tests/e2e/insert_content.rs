@@ -0,0 +1,206 @@
+//! E2E tests for "Insert File..." and "Insert Command Output..."
+
+use crate::common::harness::EditorTestHarness;
+use crossterm::event::{KeyCode, KeyModifiers};
+use fresh::config::Config;
+use std::fs;
+use tempfile::TempDir;
+
+/// Harness in a temp project with `doc.txt` holding `content` open
+fn open(content: &str) -> (TempDir, EditorTestHarness) {
+    let temp_dir = TempDir::new().unwrap();
+    let project_dir = temp_dir.path().to_path_buf();
+    let file = project_dir.join("doc.txt");
+    fs::write(&file, content).unwrap();
+    let mut harness =
+        EditorTestHarness::with_config_and_working_dir(80, 24, Config::default(), project_dir)
+            .unwrap();
+    harness.open_file(&file).unwrap();
+    harness.render().unwrap();
+    (temp_dir, harness)
+}
+
+/// Run a command from the command palette, leaving its prompt open
+fn run_command(harness: &mut EditorTestHarness, name: &str) {
+    harness
+        .send_key(KeyCode::Char('p'), KeyModifiers::CONTROL)
+        .unwrap();
+    harness.wait_for_prompt().unwrap();
+    harness.type_text(name).unwrap();
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    harness.wait_for_prompt().unwrap();
+}
+
+/// Insert the output of `command`, waiting for it to finish
+fn insert_output(harness: &mut EditorTestHarness, command: &str) {
+    run_command(harness, "Insert Command Output");
+    harness.type_text(command).unwrap();
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    harness.wait_for_prompt_closed().unwrap();
+}
+
+/// Select to the end of the line at every cursor
+fn select_to_line_end(harness: &mut EditorTestHarness) {
+    harness.send_key(KeyCode::End, KeyModifiers::SHIFT).unwrap();
+}
+
+#[test]
+fn test_insert_file_at_every_cursor_in_one_undo_step() {
+    let (temp_dir, mut harness) = open("a\nb\n");
+    fs::write(temp_dir.path().join("snippet.txt"), "X-").unwrap();
+    harness.editor_mut().add_cursor_below();
+
+    run_command(&mut harness, "Insert File");
+    harness
+        .wait_until(|h| h.screen_to_string().contains("snippet.txt"))
+        .unwrap();
+    harness.type_text("snippet").unwrap();
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    harness
+        .wait_until(|h| h.get_buffer_content().as_deref() == Some("X-a\nX-b\n"))
+        .unwrap();
+    assert_eq!(
+        harness.editor().get_status_message().map(String::as_str),
+        Some("Inserted snippet.txt")
+    );
+
+    harness
+        .send_key(KeyCode::Char('z'), KeyModifiers::CONTROL)
+        .unwrap();
+    harness.assert_buffer_content("a\nb\n");
+}
+
+#[test]
+fn test_insert_large_file_asks_first() {
+    let temp_dir = TempDir::new().unwrap();
+    let project_dir = temp_dir.path().to_path_buf();
+    let file = project_dir.join("doc.txt");
+    fs::write(&file, "").unwrap();
+    fs::write(project_dir.join("big.txt"), "0123456789".repeat(20)).unwrap();
+    let mut config = Config::default();
+    config.editor.large_file_threshold_bytes = 100;
+    let mut harness =
+        EditorTestHarness::with_config_and_working_dir(80, 24, config, project_dir).unwrap();
+    harness.open_file(&file).unwrap();
+
+    run_command(&mut harness, "Insert File");
+    harness
+        .wait_until(|h| h.screen_to_string().contains("big.txt"))
+        .unwrap();
+    harness.type_text("big").unwrap();
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    harness.wait_for_prompt().unwrap();
+    harness.assert_screen_contains("(i)nsert");
+    harness.assert_buffer_content("");
+
+    harness.type_text("i").unwrap();
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    harness.assert_buffer_content(&"0123456789".repeat(20));
+}
+
+#[test]
+#[cfg_attr(not(unix), ignore = "Shell commands require Unix-like environment")]
+fn test_insert_command_output_at_cursor() {
+    let (_temp_dir, mut harness) = open("start\n");
+    harness.send_key(KeyCode::End, KeyModifiers::NONE).unwrap();
+
+    insert_output(&mut harness, "printf ' done'");
+    harness
+        .wait_until(|h| h.get_buffer_content().as_deref() == Some("start done\n"))
+        .unwrap();
+
+    // The command runs in the directory of the buffer's file
+    harness
+        .send_key(KeyCode::Char('z'), KeyModifiers::CONTROL)
+        .unwrap();
+    insert_output(&mut harness, "ls");
+    harness
+        .wait_until(|h| h.get_buffer_content().unwrap().contains("doc.txt"))
+        .unwrap();
+}
+
+#[test]
+#[cfg_attr(not(unix), ignore = "Shell commands require Unix-like environment")]
+fn test_insert_command_output_filters_selection() {
+    let (_temp_dir, mut harness) = open("pear\napple\npear\nfig\n");
+    harness
+        .send_key(KeyCode::Char('a'), KeyModifiers::CONTROL)
+        .unwrap();
+
+    insert_output(&mut harness, "sort -u");
+    harness
+        .wait_until(|h| h.get_buffer_content().as_deref() == Some("apple\nfig\npear\n"))
+        .unwrap();
+
+    harness
+        .send_key(KeyCode::Char('z'), KeyModifiers::CONTROL)
+        .unwrap();
+    harness.assert_buffer_content("pear\napple\npear\nfig\n");
+}
+
+#[test]
+#[cfg_attr(not(unix), ignore = "Shell commands require Unix-like environment")]
+fn test_insert_command_output_filters_each_cursor() {
+    let (_temp_dir, mut harness) = open("one\ntwo\nthree\n");
+    harness.editor_mut().add_cursor_below();
+    select_to_line_end(&mut harness);
+
+    insert_output(&mut harness, "tr a-z A-Z");
+    harness
+        .wait_until(|h| h.get_buffer_content().as_deref() == Some("ONE\nTWO\nthree\n"))
+        .unwrap();
+
+    // One undo step for both cursors
+    harness
+        .send_key(KeyCode::Char('z'), KeyModifiers::CONTROL)
+        .unwrap();
+    harness.assert_buffer_content("one\ntwo\nthree\n");
+}
+
+#[test]
+#[cfg_attr(not(unix), ignore = "Shell commands require Unix-like environment")]
+fn test_insert_command_output_without_stdin_replaces_selection() {
+    let (_temp_dir, mut harness) = open("one\n");
+    select_to_line_end(&mut harness);
+
+    run_command(&mut harness, "Insert Command Output");
+    harness.assert_screen_contains("Filter selections through:");
+    harness
+        .send_key(KeyCode::Char('i'), KeyModifiers::ALT)
+        .unwrap();
+    harness.render().unwrap();
+    harness.assert_screen_contains("Insert output of:");
+    harness.type_text("printf two").unwrap();
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    harness
+        .wait_until(|h| h.get_buffer_content().as_deref() == Some("two\n"))
+        .unwrap();
+}
+
+#[test]
+#[cfg_attr(not(unix), ignore = "Shell commands require Unix-like environment")]
+fn test_insert_command_output_failure_inserts_nothing() {
+    let (_temp_dir, mut harness) = open("keep\n");
+
+    insert_output(&mut harness, "echo partial; echo broken >&2; exit 3");
+    harness
+        .wait_until(|h| {
+            h.editor()
+                .get_status_message()
+                .is_some_and(|message| message.ends_with("nothing inserted"))
+        })
+        .unwrap();
+    harness.assert_buffer_content("keep\n");
+}
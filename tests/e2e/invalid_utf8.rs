@@ -0,0 +1,85 @@
+use crate::common::harness::EditorTestHarness;
+use crossterm::event::{KeyCode, KeyModifiers};
+use std::fs;
+use tempfile::TempDir;
+
+/// A text file with a stray Latin-1 byte and a run of two invalid bytes
+const CONTENTS: &[u8] = b"name: caf\xe9\nlog \xff\xfe tail\n";
+
+fn open_invalid_file() -> (EditorTestHarness, TempDir, std::path::PathBuf) {
+    let temp_dir = TempDir::new().unwrap();
+    let file_path = temp_dir.path().join("mixed.log");
+    fs::write(&file_path, CONTENTS).unwrap();
+    let mut harness = EditorTestHarness::new(200, 24).unwrap();
+    harness.open_file(&file_path).unwrap();
+    harness.render().unwrap();
+    (harness, temp_dir, file_path)
+}
+
+fn save(harness: &mut EditorTestHarness) {
+    harness
+        .send_key(KeyCode::Char('s'), KeyModifiers::CONTROL)
+        .unwrap();
+    harness.render().unwrap();
+}
+
+/// Invalid bytes show as `<XX>`, the status bar counts them, and saving
+/// without edits writes the file back byte for byte
+#[test]
+fn test_invalid_utf8_round_trips_unchanged() {
+    let (mut harness, _temp_dir, file_path) = open_invalid_file();
+
+    assert!(!harness.editor().is_editing_disabled());
+    harness.assert_screen_contains("name: caf<E9>");
+    harness.assert_screen_contains("log <FF><FE> tail");
+    harness.assert_screen_contains("contains 2 invalid byte sequences");
+
+    save(&mut harness);
+    assert_eq!(fs::read(&file_path).unwrap(), CONTENTS);
+}
+
+/// Editing around the invalid bytes keeps them
+#[test]
+fn test_invalid_utf8_kept_through_edits_elsewhere() {
+    let (mut harness, _temp_dir, file_path) = open_invalid_file();
+
+    harness.type_text("# ").unwrap();
+    harness.send_key(KeyCode::Down, KeyModifiers::NONE).unwrap();
+    harness.send_key(KeyCode::End, KeyModifiers::NONE).unwrap();
+    harness.type_text("!").unwrap();
+    save(&mut harness);
+
+    assert_eq!(
+        fs::read(&file_path).unwrap(),
+        b"# name: caf\xe9\nlog \xff\xfe tail!\n"
+    );
+}
+
+/// Deleting an invalid byte drops it from the file and says so; undo
+/// brings it back
+#[test]
+fn test_deleting_invalid_byte_drops_it_with_note() {
+    let (mut harness, _temp_dir, file_path) = open_invalid_file();
+
+    harness.send_key(KeyCode::End, KeyModifiers::NONE).unwrap();
+    harness
+        .send_key(KeyCode::Backspace, KeyModifiers::NONE)
+        .unwrap();
+    harness.render().unwrap();
+    harness.assert_screen_contains("Removed 1 invalid byte(s)");
+    harness.assert_screen_contains("contains 1 invalid byte sequences");
+    harness.assert_screen_contains("name: caf");
+    harness.assert_screen_not_contains("<E9>");
+
+    save(&mut harness);
+    assert_eq!(
+        fs::read(&file_path).unwrap(),
+        b"name: caf\nlog \xff\xfe tail\n"
+    );
+
+    harness
+        .send_key(KeyCode::Char('z'), KeyModifiers::CONTROL)
+        .unwrap();
+    save(&mut harness);
+    assert_eq!(fs::read(&file_path).unwrap(), CONTENTS);
+}
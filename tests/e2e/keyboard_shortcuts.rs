@@ -0,0 +1,134 @@
+//! E2E tests for keymap provenance and the keyboard shortcuts view
+
+use crate::common::harness::EditorTestHarness;
+use crossterm::event::{KeyCode, KeyModifiers};
+use fresh::config::{Config, Keybinding};
+use fresh::input::keybindings::{BindingSource, KeyContext};
+use fresh::services::plugins::api::PluginCommand;
+use std::collections::HashMap;
+
+const CTRL_K: [(KeyCode, KeyModifiers); 1] = [(KeyCode::Char('k'), KeyModifiers::CONTROL)];
+
+/// An editor whose config rebinds Ctrl+K
+fn harness_with_user_binding(width: u16, height: u16) -> EditorTestHarness {
+    let mut config = Config::default();
+    config.keybindings.push(Keybinding {
+        key: "k".to_string(),
+        modifiers: vec!["ctrl".to_string()],
+        keys: vec![],
+        action: "command_palette".to_string(),
+        args: HashMap::new(),
+        when: None,
+    });
+    EditorTestHarness::with_config(width, height, config).unwrap()
+}
+
+/// Activate an editor mode binding Ctrl+K, defined the way a plugin does
+fn enable_plugin_mode(harness: &mut EditorTestHarness) {
+    let editor = harness.editor_mut();
+    editor
+        .handle_plugin_command(PluginCommand::DefineMode {
+            name: "quick-nav".to_string(),
+            parent: None,
+            bindings: vec![("C-k".to_string(), "quick_nav_jump".to_string())],
+            read_only: false,
+            plugin: Some("keymap_demo".to_string()),
+        })
+        .unwrap();
+    editor
+        .handle_plugin_command(PluginCommand::SetEditorMode {
+            mode: Some("quick-nav".to_string()),
+        })
+        .unwrap();
+}
+
+/// A user binding overrides the default, and a plugin mode shadows both
+#[test]
+fn test_keymap_provenance_user_then_plugin_mode() {
+    let mut harness = harness_with_user_binding(80, 24);
+
+    let keymap = harness.editor().effective_keymap(KeyContext::Normal);
+    let entry = keymap.iter().find(|e| e.keys == CTRL_K).unwrap();
+    assert_eq!(entry.action, "command_palette");
+    assert_eq!(entry.source, BindingSource::User);
+    assert_eq!(entry.shadowed.len(), 1);
+    assert_eq!(entry.shadowed[0].action, "delete_to_line_end");
+    assert!(matches!(entry.shadowed[0].source, BindingSource::Keymap(_)));
+
+    enable_plugin_mode(&mut harness);
+    let keymap = harness.editor().effective_keymap(KeyContext::Normal);
+    let entry = keymap.iter().find(|e| e.keys == CTRL_K).unwrap();
+    assert_eq!(entry.action, "quick_nav_jump");
+    assert_eq!(
+        entry.source,
+        BindingSource::Mode {
+            mode: "quick-nav".to_string(),
+            plugin: Some("keymap_demo".to_string()),
+        }
+    );
+    let shadowed: Vec<_> = entry
+        .shadowed
+        .iter()
+        .map(|s| (s.action.as_str(), &s.source))
+        .collect();
+    assert_eq!(shadowed.len(), 2);
+    assert_eq!(shadowed[0], ("command_palette", &BindingSource::User));
+    assert_eq!(shadowed[1].0, "delete_to_line_end");
+    assert!(matches!(shadowed[1].1, BindingSource::Keymap(_)));
+
+    // Modes only apply where buffer keys are handled
+    let keymap = harness.editor().effective_keymap(KeyContext::Prompt);
+    let entry = keymap.iter().find(|e| e.keys == CTRL_K).unwrap();
+    assert_eq!(entry.action, "prompt_delete_to_line_end");
+}
+
+/// The view shows sources and conflicts, and `/` filters it as you type
+#[test]
+fn test_keyboard_shortcuts_view_lists_conflicts_and_filters() {
+    let mut harness = harness_with_user_binding(200, 40);
+    enable_plugin_mode(&mut harness);
+
+    harness.editor_mut().open_keyboard_shortcuts();
+    harness.render().unwrap();
+    harness.assert_screen_contains("Keyboard Shortcuts (normal context)");
+    harness.assert_screen_contains("── Mode quick-nav ──");
+    harness.assert_screen_contains("── Context global ──");
+
+    harness
+        .send_key(KeyCode::Char('/'), KeyModifiers::NONE)
+        .unwrap();
+    harness.type_text("quick_nav").unwrap();
+    harness.render().unwrap();
+    harness.assert_screen_contains("Filter \"quick_nav\": 1 of");
+    harness.assert_screen_contains("[plugin keymap_demo mode quick-nav]");
+    harness.assert_screen_contains(
+        "quick_nav_jump (plugin keymap_demo mode quick-nav) wins over command_palette (user config)",
+    );
+    harness.assert_screen_contains(
+        "quick_nav_jump (plugin keymap_demo mode quick-nav) wins over delete_to_line_end (keymap",
+    );
+    harness.assert_screen_not_contains("── Context global ──");
+
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    harness.render().unwrap();
+    harness.assert_screen_contains("Filter \"quick_nav\": 1 of");
+
+    // Filtering by key finds the binding too
+    harness
+        .send_key(KeyCode::Char('/'), KeyModifiers::NONE)
+        .unwrap();
+    for _ in "quick_nav".chars() {
+        harness
+            .send_key(KeyCode::Backspace, KeyModifiers::NONE)
+            .unwrap();
+    }
+    harness.type_text("ctrl+k").unwrap();
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    harness.render().unwrap();
+    harness.assert_screen_contains("Filter \"ctrl+k\"");
+    harness.assert_screen_contains("quick_nav_jump");
+}
@@ -1,4 +1,10 @@
 use crate::common::harness::EditorTestHarness;
+use crossterm::event::{KeyCode, KeyModifiers};
+use fresh::config::Config;
+use fresh::config_io::DirectoryContext;
+use fresh::services::recent_projects::RecentProjects;
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
 
 /// Test that editor doesn't quit prematurely
 #[test]
@@ -12,3 +18,99 @@ fn test_editor_lifecycle() {
     // - Send quit command
     // - Verify should_quit() returns true
 }
+
+/// Projects "current" (the working directory) and "other" exist, "gone" was
+/// deleted; all three are in the recent projects, "other" most recently
+fn recent_projects_setup(temp: &TempDir) -> (DirectoryContext, PathBuf, PathBuf) {
+    let dir_context = DirectoryContext::for_testing(temp.path());
+    let current = temp.path().join("current");
+    let other = temp.path().join("other");
+    let gone = temp.path().join("gone");
+    std::fs::create_dir_all(&current).unwrap();
+    std::fs::create_dir_all(&other).unwrap();
+
+    let mut recent = RecentProjects::default();
+    recent.record(&gone, 100);
+    recent.record(&other, 200);
+    recent.record(&current, 300);
+    recent.save(&dir_context.recent_projects_path()).unwrap();
+    (
+        dir_context,
+        current.canonicalize().unwrap(),
+        other.canonicalize().unwrap(),
+    )
+}
+
+fn open_recent_projects(dir_context: &DirectoryContext, working_dir: &Path) -> EditorTestHarness {
+    let mut harness = EditorTestHarness::with_shared_dir_context(
+        100,
+        24,
+        Config::default(),
+        working_dir.to_path_buf(),
+        dir_context.clone(),
+    )
+    .unwrap();
+    harness
+        .send_key(KeyCode::Char('p'), KeyModifiers::CONTROL)
+        .unwrap();
+    harness.type_text("Open Recent Project").unwrap();
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    harness.render().unwrap();
+    harness
+}
+
+/// Choosing a recent project restarts the editor in it and moves it to the
+/// front of the list
+#[test]
+fn test_switch_project_through_recent_projects_picker() {
+    let temp = TempDir::new().unwrap();
+    let (dir_context, current, other) = recent_projects_setup(&temp);
+    let mut harness = open_recent_projects(&dir_context, &current);
+
+    // The current project isn't offered; the others are, most recent first,
+    // with their paths shortened to fit
+    harness.assert_screen_contains("Recent project:");
+    harness.assert_screen_contains("/other");
+    harness.assert_screen_contains("/gone");
+    harness.assert_screen_contains("Missing");
+    harness.assert_screen_contains("Open Directory");
+    assert!(!harness.should_quit());
+
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    assert!(harness.should_quit());
+    assert_eq!(harness.editor_mut().take_restart_dir(), Some(other.clone()));
+
+    let recent = RecentProjects::load(&dir_context.recent_projects_path());
+    assert_eq!(recent.projects[0].path, other);
+    assert!(recent.projects[0].last_opened > 300);
+    assert_eq!(recent.projects.len(), 3);
+}
+
+/// Choosing a project whose directory is gone offers to drop it instead
+#[test]
+fn test_remove_missing_project_from_recent_projects() {
+    let temp = TempDir::new().unwrap();
+    let (dir_context, current, other) = recent_projects_setup(&temp);
+    let mut harness = open_recent_projects(&dir_context, &current);
+
+    harness.send_key(KeyCode::Down, KeyModifiers::NONE).unwrap();
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    harness.render().unwrap();
+    harness.assert_screen_contains("no longer exists");
+    harness.type_text("y").unwrap();
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+
+    assert!(!harness.should_quit());
+    assert_eq!(harness.editor_mut().take_restart_dir(), None);
+    let recent = RecentProjects::load(&dir_context.recent_projects_path());
+    let paths: Vec<&Path> = recent.projects.iter().map(|p| p.path.as_path()).collect();
+    assert_eq!(paths, [current.as_path(), other.as_path()]);
+}
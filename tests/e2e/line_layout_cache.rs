@@ -0,0 +1,143 @@
+//! E2E tests for the per-line layout cache: typing and cursor movement only
+//! lay out the lines that changed
+
+use crate::common::harness::EditorTestHarness;
+use crossterm::event::{KeyCode, KeyModifiers};
+use fresh::model::event::{Event, OverlayFace};
+use fresh::view::overlay::OverlayNamespace;
+use tempfile::TempDir;
+
+const LINES: usize = 50_000;
+/// Every line is "NNNNN text\n"
+const LINE_LEN: usize = 11;
+
+fn harness_with_big_buffer(temp_dir: &TempDir) -> EditorTestHarness {
+    let path = temp_dir.path().join("big.txt");
+    let content: String = (0..LINES).map(|i| format!("{:05} text\n", i)).collect();
+    std::fs::write(&path, content).unwrap();
+
+    let mut harness = EditorTestHarness::new(100, 40).unwrap();
+    harness.open_file(&path).unwrap();
+
+    // 500 overlays around the middle of the buffer, many of them on screen
+    let state = harness.editor_mut().active_state_mut();
+    for line in LINES / 2 - 250..LINES / 2 + 250 {
+        let start = line * LINE_LEN;
+        state.apply(&Event::AddOverlay {
+            namespace: Some(OverlayNamespace::from_string("layout_test".to_string())),
+            range: start..start + 5,
+            face: OverlayFace::Background {
+                color: (40, 40, 80),
+            },
+            priority: 10,
+            message: None,
+            extend_to_line_end: false,
+        });
+    }
+
+    harness
+        .send_key(KeyCode::Char('g'), KeyModifiers::CONTROL)
+        .unwrap();
+    harness.type_text(&(LINES / 2).to_string()).unwrap();
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    harness.render().unwrap();
+    harness
+}
+
+fn laid_out(harness: &EditorTestHarness) -> usize {
+    harness.editor().active_line_layout().lines_laid_out()
+}
+
+#[test]
+fn test_typing_lays_out_only_the_edited_line() {
+    let temp_dir = TempDir::new().unwrap();
+    let mut harness = harness_with_big_buffer(&temp_dir);
+
+    let start = laid_out(&harness);
+    for i in 0..1000 {
+        let before = laid_out(&harness);
+        harness
+            .send_key(KeyCode::Char('x'), KeyModifiers::NONE)
+            .unwrap();
+        let work = laid_out(&harness) - before;
+        assert!(
+            work <= 1,
+            "keystroke {} laid out {} lines instead of just the edited one",
+            i,
+            work
+        );
+    }
+    assert!(laid_out(&harness) - start <= 1000);
+    harness.assert_screen_contains("xxxxx24999 text");
+}
+
+#[test]
+fn test_cursor_movement_reuses_layout() {
+    let temp_dir = TempDir::new().unwrap();
+    let mut harness = harness_with_big_buffer(&temp_dir);
+
+    let before = laid_out(&harness);
+    harness
+        .send_key(KeyCode::Right, KeyModifiers::NONE)
+        .unwrap();
+    harness.send_key(KeyCode::Down, KeyModifiers::NONE).unwrap();
+    harness.send_key(KeyCode::Up, KeyModifiers::NONE).unwrap();
+    assert_eq!(laid_out(&harness), before);
+}
+
+#[test]
+fn test_scrolling_lays_out_the_whole_viewport() {
+    let temp_dir = TempDir::new().unwrap();
+    let mut harness = harness_with_big_buffer(&temp_dir);
+
+    let before = laid_out(&harness);
+    harness
+        .send_key(KeyCode::PageDown, KeyModifiers::NONE)
+        .unwrap();
+    assert!(
+        laid_out(&harness) - before >= 30,
+        "scrolling should lay out every visible line"
+    );
+}
+
+/// Cycling through buffers that didn't change shows each one where it was
+/// from the layouts kept when it was left, without laying out any line
+#[test]
+fn test_switching_back_to_unchanged_buffers_reuses_layout() {
+    let temp_dir = TempDir::new().unwrap();
+    let mut harness = EditorTestHarness::new(100, 40).unwrap();
+    const BUFFERS: usize = 5;
+    for i in 0..BUFFERS {
+        let path = temp_dir.path().join(format!("file{}.txt", i));
+        let content: String = (0..2_000).map(|n| format!("{} line {}\n", i, n)).collect();
+        std::fs::write(&path, content).unwrap();
+        harness.open_file(&path).unwrap();
+    }
+
+    // Scroll each buffer somewhere different
+    for i in 0..BUFFERS {
+        harness.editor_mut().next_buffer();
+        harness
+            .send_key(KeyCode::Char('g'), KeyModifiers::CONTROL)
+            .unwrap();
+        harness.type_text(&(100 * (i + 1)).to_string()).unwrap();
+        harness
+            .send_key(KeyCode::Enter, KeyModifiers::NONE)
+            .unwrap();
+        harness.render().unwrap();
+    }
+
+    for _ in 0..4 * BUFFERS {
+        harness.editor_mut().next_buffer();
+        let before = laid_out(&harness);
+        harness.render().unwrap();
+        assert_eq!(
+            laid_out(&harness),
+            before,
+            "switching back to an unchanged buffer laid out lines"
+        );
+    }
+    harness.assert_screen_contains("4 line 499");
+}
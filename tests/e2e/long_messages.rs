@@ -0,0 +1,93 @@
+//! E2E tests for status messages and prompt input too long for the terminal
+
+use crate::common::harness::EditorTestHarness;
+use crossterm::event::{KeyCode, KeyModifiers};
+use fresh::services::plugins::api::PluginCommand;
+use fresh::view::prompt::PromptType;
+
+const LONG_PATH: &str = "/home/user/projects/fresh/crates/editor/src/view/ui/widgets/status_bar.rs";
+
+fn execute_action(harness: &mut EditorTestHarness, name: &str) {
+    harness
+        .editor_mut()
+        .handle_plugin_command(PluginCommand::ExecuteAction {
+            action_name: name.to_string(),
+        })
+        .unwrap();
+    harness.render().unwrap();
+}
+
+/// A status message too long for the bar loses the middle of its path, not
+/// the file name or the reason at its end
+#[test]
+fn test_long_status_message_shortens_the_path() {
+    let mut harness = EditorTestHarness::new(120, 20).unwrap();
+    let message = format!("Failed to save {LONG_PATH}: Permission denied");
+    harness.editor_mut().set_status_message(message);
+    harness.render().unwrap();
+
+    let status = harness.get_status_bar();
+    assert!(status.contains("Failed to save /home/"), "{status}");
+    assert!(
+        status.contains("status_bar.rs: Permission denied"),
+        "{status}"
+    );
+    assert!(!status.contains("/projects/fresh/crates/"), "{status}");
+}
+
+/// "Show Full Message" shows the whole message, and Enter copies it
+#[test]
+fn test_show_full_message_popup_copies_the_message() {
+    let mut harness = EditorTestHarness::new(100, 20).unwrap();
+    harness.editor_mut().set_clipboard_for_test(String::new());
+    let message = format!("Failed to save {LONG_PATH}: Permission denied");
+    harness.editor_mut().set_status_message(message.clone());
+
+    execute_action(&mut harness, "show_full_message");
+    harness.assert_screen_contains("Status Message");
+    harness.assert_screen_contains(LONG_PATH);
+
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    harness.render().unwrap();
+    harness.assert_screen_not_contains(LONG_PATH);
+    harness.assert_screen_contains("Message copied to clipboard");
+
+    harness.editor_mut().paste_for_test();
+    assert_eq!(harness.get_buffer_content().unwrap(), message);
+}
+
+/// Prompt input wider than the field scrolls to keep the cursor visible,
+/// with "<" and ">" marking the hidden ends
+#[test]
+fn test_long_prompt_input_scrolls_with_indicators() {
+    let mut harness = EditorTestHarness::new(40, 20).unwrap();
+    harness
+        .editor_mut()
+        .start_prompt("Find: ".to_string(), PromptType::Search);
+    let input = "abcdefghijklmnopqrstuvwxyz0123456789ABCDEF";
+    harness.type_text(input).unwrap();
+    harness.render().unwrap();
+
+    // Cursor at the end: the start is hidden, the end and the cursor show
+    let prompt_line = harness.get_prompt_line();
+    assert!(prompt_line.starts_with("Find: <"), "{prompt_line:?}");
+    assert!(
+        prompt_line.trim_end().ends_with("ABCDEF"),
+        "{prompt_line:?}"
+    );
+    assert!(!prompt_line.contains("abc"), "{prompt_line:?}");
+    let (cursor_x, _) = harness.screen_cursor_position();
+    assert_eq!(cursor_x, 39);
+
+    // Cursor at the start: the end is hidden instead
+    harness.send_key(KeyCode::Home, KeyModifiers::NONE).unwrap();
+    harness.render().unwrap();
+    let prompt_line = harness.get_prompt_line();
+    assert!(prompt_line.starts_with("Find: abcdef"), "{prompt_line:?}");
+    assert!(prompt_line.trim_end().ends_with('>'), "{prompt_line:?}");
+    assert!(!prompt_line.contains("ABCDEF"), "{prompt_line:?}");
+    let (cursor_x, _) = harness.screen_cursor_position();
+    assert_eq!(cursor_x, 6);
+}
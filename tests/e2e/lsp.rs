@@ -1,5 +1,6 @@
 //! E2E tests for LSP features
 
+use crate::common::fixtures::TestFixture;
 use crate::common::harness::EditorTestHarness;
 use crossterm::event::{KeyCode, KeyModifiers};
 
@@ -136,32 +137,70 @@ fn test_lsp_completion_replaces_word() -> std::io::Result<()> {
     Ok(())
 }
 
+/// Publish `diagnostics` for `path` through the async bridge, as a language
+/// server would, and render
+fn publish_diagnostics(
+    harness: &mut EditorTestHarness,
+    path: &std::path::Path,
+    diagnostics: Vec<lsp_types::Diagnostic>,
+) -> std::io::Result<()> {
+    use fresh::services::async_bridge::AsyncMessage;
+
+    let uri = fresh::services::lsp::uri::path_to_uri(path).expect("Should create URI");
+    harness
+        .editor()
+        .async_bridge()
+        .unwrap()
+        .sender()
+        .send(AsyncMessage::LspDiagnostics {
+            language: "rust".to_string(),
+            uri: uri.as_str().to_string(),
+            diagnostics,
+        })
+        .unwrap();
+    harness.editor_mut().process_async_messages();
+    harness.render()
+}
+
+fn diagnostic(
+    line: u32,
+    start: u32,
+    end: u32,
+    severity: lsp_types::DiagnosticSeverity,
+    message: &str,
+) -> lsp_types::Diagnostic {
+    lsp_types::Diagnostic {
+        range: lsp_types::Range::new(
+            lsp_types::Position::new(line, start),
+            lsp_types::Position::new(line, end),
+        ),
+        severity: Some(severity),
+        message: message.to_string(),
+        ..Default::default()
+    }
+}
+
 /// Test LSP diagnostics display in the editor
 #[test]
 fn test_lsp_diagnostics_display() -> std::io::Result<()> {
-    use fresh::model::event::{Event, OverlayFace};
-    use fresh::view::overlay::OverlayNamespace;
+    use lsp_types::DiagnosticSeverity;
 
+    let fixture = TestFixture::new("main.txt", "let x = 5;\n")?;
     let mut harness = EditorTestHarness::new(80, 24)?;
-
-    // Type some text
-    harness.type_text("let x = 5;")?;
-    harness.render()?;
-
-    // Manually add a diagnostic overlay (simulating what LSP would do)
-    let state = harness.editor_mut().active_state_mut();
-    state.apply(&Event::AddOverlay {
-        namespace: Some(OverlayNamespace::from_string("lsp-diagnostic".to_string())),
-        range: 4..5, // "x"
-        face: OverlayFace::Background {
-            color: (40, 0, 0), // Dark red background
-        },
-        priority: 100,
-        message: Some("unused variable: `x`".to_string()),
-        extend_to_line_end: false,
-    });
-
-    harness.render()?;
+    harness.open_file(&fixture.path)?;
+    harness.render()?;
+
+    publish_diagnostics(
+        &mut harness,
+        &fixture.path,
+        vec![diagnostic(
+            0,
+            4,
+            5,
+            DiagnosticSeverity::ERROR,
+            "unused variable: `x`",
+        )],
+    )?;
 
     // Verify the diagnostic is displayed in the status bar
     let screen = harness.screen_to_string();
@@ -252,37 +291,22 @@ fn test_lsp_completion_popup() -> std::io::Result<()> {
 /// Test LSP diagnostics summary in status bar
 #[test]
 fn test_lsp_diagnostics_status_bar() -> std::io::Result<()> {
-    use fresh::model::event::{Event, OverlayFace};
-    use fresh::view::overlay::OverlayNamespace;
+    use lsp_types::DiagnosticSeverity;
 
+    let fixture = TestFixture::new("main.txt", "let x = 5;\nlet y = 10;\n")?;
     let mut harness = EditorTestHarness::new(80, 24)?;
-
-    // Type some text
-    harness.type_text("let x = 5;\nlet y = 10;")?;
+    harness.open_file(&fixture.path)?;
     harness.render()?;
 
-    // Add error diagnostic
-    let state = harness.editor_mut().active_state_mut();
-    state.apply(&Event::AddOverlay {
-        namespace: Some(OverlayNamespace::from_string("lsp-diagnostic".to_string())),
-        range: 4..5,
-        face: OverlayFace::Background { color: (40, 0, 0) },
-        priority: 100, // Error priority
-        message: Some("unused variable: `x`".to_string()),
-        extend_to_line_end: false,
-    });
-
-    // Add warning diagnostic
-    state.apply(&Event::AddOverlay {
-        namespace: Some(OverlayNamespace::from_string("lsp-diagnostic".to_string())),
-        range: 15..16,
-        face: OverlayFace::Background { color: (40, 40, 0) },
-        priority: 50, // Warning priority
-        message: Some("unused variable: `y`".to_string()),
-        extend_to_line_end: false,
-    });
-
-    harness.render()?;
+    // One error and one warning
+    publish_diagnostics(
+        &mut harness,
+        &fixture.path,
+        vec![
+            diagnostic(0, 4, 5, DiagnosticSeverity::ERROR, "unused variable: `x`"),
+            diagnostic(1, 4, 5, DiagnosticSeverity::WARNING, "unused variable: `y`"),
+        ],
+    )?;
 
     // Verify status bar shows both error and warning counts
     let screen = harness.screen_to_string();
@@ -301,39 +325,25 @@ fn test_lsp_diagnostics_status_bar() -> std::io::Result<()> {
 /// Test that diagnostics are removed when cleared
 #[test]
 fn test_lsp_clear_diagnostics() -> std::io::Result<()> {
-    use fresh::model::event::{Event, OverlayFace};
-    use fresh::view::overlay::OverlayNamespace;
+    use lsp_types::DiagnosticSeverity;
 
+    let fixture = TestFixture::new("main.txt", "let x = 5;\n")?;
     let mut harness = EditorTestHarness::new(80, 24)?;
-
-    // Type some text
-    harness.type_text("let x = 5;")?;
+    harness.open_file(&fixture.path)?;
     harness.render()?;
 
-    // Add diagnostic
-    let state = harness.editor_mut().active_state_mut();
-    state.apply(&Event::AddOverlay {
-        namespace: Some(OverlayNamespace::from_string("lsp-diagnostic".to_string())),
-        range: 4..5,
-        face: OverlayFace::Background { color: (40, 0, 0) },
-        priority: 100,
-        message: Some("test error".to_string()),
-        extend_to_line_end: false,
-    });
-
-    harness.render()?;
+    publish_diagnostics(
+        &mut harness,
+        &fixture.path,
+        vec![diagnostic(0, 4, 5, DiagnosticSeverity::ERROR, "test error")],
+    )?;
 
     // Verify diagnostic is shown
     let screen = harness.screen_to_string();
     assert!(screen.contains("E:1"), "Expected error count in status bar");
 
-    // Clear diagnostics using namespace
-    let state = harness.editor_mut().active_state_mut();
-    state.apply(&Event::ClearNamespace {
-        namespace: OverlayNamespace::from_string("lsp-diagnostic".to_string()),
-    });
-
-    harness.render()?;
+    // The server publishes an empty list once the error is fixed
+    publish_diagnostics(&mut harness, &fixture.path, Vec::new())?;
 
     // Verify diagnostic is removed
     let screen = harness.screen_to_string();
@@ -798,7 +808,10 @@ fn test_lsp_waiting_indicator() -> std::io::Result<()> {
             auto_start: false,
             process_limits: fresh::services::process_limits::ProcessLimits::default(),
             initialization_options: None,
-        },
+            name: None,
+            primary: false,
+        }
+        .into(),
     );
 
     // Create harness with config
@@ -973,7 +986,10 @@ fn test_lsp_completion_canceled_on_cursor_move() -> std::io::Result<()> {
             auto_start: false,
             process_limits: fresh::services::process_limits::ProcessLimits::default(),
             initialization_options: None,
-        },
+            name: None,
+            primary: false,
+        }
+        .into(),
     );
 
     // Create harness with config
@@ -1038,7 +1054,10 @@ fn test_lsp_cursor_animation() -> std::io::Result<()> {
             auto_start: false,
             process_limits: fresh::services::process_limits::ProcessLimits::default(),
             initialization_options: None,
-        },
+            name: None,
+            primary: false,
+        }
+        .into(),
     );
 
     // Create harness with config
@@ -1104,7 +1123,10 @@ fn test_lsp_completion_canceled_on_text_edit() -> std::io::Result<()> {
             auto_start: false,
             process_limits: fresh::services::process_limits::ProcessLimits::default(),
             initialization_options: None,
-        },
+            name: None,
+            primary: false,
+        }
+        .into(),
     );
 
     // Create harness with config
@@ -1476,6 +1498,7 @@ fn test_lsp_typing_performance_with_many_diagnostics() -> std::io::Result<()> {
     fresh::services::lsp::diagnostics::apply_diagnostics_to_state(
         state,
         &diag_params.diagnostics,
+        fresh::model::position_encoding::PositionEncoding::Utf16,
         &fresh::view::theme::Theme::dark(),
     );
 
@@ -1512,6 +1535,7 @@ fn test_lsp_typing_performance_with_many_diagnostics() -> std::io::Result<()> {
         fresh::services::lsp::diagnostics::apply_diagnostics_to_state_cached(
             state,
             &diag_params.diagnostics,
+            fresh::model::position_encoding::PositionEncoding::Utf16,
             &fresh::view::theme::Theme::dark(),
         );
         let reapply_duration = start.elapsed();
@@ -1688,7 +1712,10 @@ fn test_lsp_diagnostics_non_blocking() -> std::io::Result<()> {
             auto_start: false,
             process_limits: fresh::services::process_limits::ProcessLimits::default(),
             initialization_options: None,
-        },
+            name: None,
+            primary: false,
+        }
+        .into(),
     );
 
     // Create harness with config and working directory
@@ -1845,7 +1872,10 @@ fn test_rust_analyzer_rename_real_scenario() -> std::io::Result<()> {
             auto_start: false,
             process_limits: fresh::services::process_limits::ProcessLimits::default(),
             initialization_options: None,
-        },
+            name: None,
+            primary: false,
+        }
+        .into(),
     );
 
     // CRITICAL: Set working directory to the temp project so rust-analyzer
@@ -2453,7 +2483,10 @@ fn test_lsp_progress_status_display() -> std::io::Result<()> {
             auto_start: false,
             process_limits: fresh::services::process_limits::ProcessLimits::default(),
             initialization_options: None,
-        },
+            name: None,
+            primary: false,
+        }
+        .into(),
     );
 
     // Create harness with config and working directory
@@ -2613,7 +2646,10 @@ fn test_lsp_crash_detection_and_restart() -> std::io::Result<()> {
             auto_start: false,
             process_limits: fresh::services::process_limits::ProcessLimits::default(),
             initialization_options: None,
-        },
+            name: None,
+            primary: false,
+        }
+        .into(),
     );
 
     // Create harness with config and working directory
@@ -2823,6 +2859,7 @@ fn test_pull_diagnostics_message_handling() -> std::io::Result<()> {
     // Send the pulled diagnostics message through the async bridge
     if let Some(bridge) = harness.editor().async_bridge() {
         let _ = bridge.sender().send(AsyncMessage::LspPulledDiagnostics {
+            language: "rust".to_string(),
             request_id: 1,
             uri: uri.as_str().to_string(),
             result_id: Some("test-result-id-123".to_string()),
@@ -2869,6 +2906,7 @@ fn test_pull_diagnostics_unchanged_response() -> std::io::Result<()> {
     // Send an unchanged response (simulating server returning same diagnostics)
     if let Some(bridge) = harness.editor().async_bridge() {
         let _ = bridge.sender().send(AsyncMessage::LspPulledDiagnostics {
+            language: "rust".to_string(),
             request_id: 2,
             uri: uri.as_str().to_string(),
             result_id: Some("test-result-id-456".to_string()),
@@ -2909,7 +2947,10 @@ fn test_pull_diagnostics_auto_trigger_after_open() -> std::io::Result<()> {
             auto_start: false,
             process_limits: fresh::services::process_limits::ProcessLimits::default(),
             initialization_options: None,
-        },
+            name: None,
+            primary: false,
+        }
+        .into(),
     );
 
     // Create a temp directory and test file
@@ -2987,7 +3028,10 @@ fn test_pull_diagnostics_result_id_tracking() -> std::io::Result<()> {
             auto_start: false,
             process_limits: fresh::services::process_limits::ProcessLimits::default(),
             initialization_options: None,
-        },
+            name: None,
+            primary: false,
+        }
+        .into(),
     );
 
     // Create a temp directory and test file
@@ -3244,7 +3288,10 @@ fn test_stopped_lsp_does_not_auto_restart_on_edit() -> std::io::Result<()> {
             auto_start: true, // Auto-start so it starts when we open the file
             process_limits: fresh::services::process_limits::ProcessLimits::default(),
             initialization_options: None,
-        },
+            name: None,
+            primary: false,
+        }
+        .into(),
     );
 
     // Create harness with config and working directory
@@ -3560,7 +3607,10 @@ fn test_hover_popup_persists_within_symbol_and_popup() -> std::io::Result<()> {
             auto_start: false,
             process_limits: fresh::services::process_limits::ProcessLimits::default(),
             initialization_options: None,
-        },
+            name: None,
+            primary: false,
+        }
+        .into(),
     );
 
     // Create harness with config
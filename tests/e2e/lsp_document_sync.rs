@@ -0,0 +1,187 @@
+//! E2E tests for LSP document sync
+//!
+//! These tests verify that edits reach a language server the way it asked
+//! for at initialization: as ranges counted in its position encoding for
+//! incremental sync, and as the whole text once edits pause for full sync.
+
+use crate::common::fake_lsp::FakeLspServer;
+use crate::common::harness::EditorTestHarness;
+use crossterm::event::{KeyCode, KeyModifiers};
+use std::path::Path;
+use std::time::Duration;
+
+/// The first line has an emoji, which is 4 bytes of UTF-8 and a surrogate
+/// pair of UTF-16
+const CONTENT: &str = "a😀b\nline\n";
+
+/// Open `CONTENT` with the sync logging server, announcing `encoding` and
+/// `sync_kind`, and wait until the server has the document
+fn open_with_server(
+    temp_dir: &Path,
+    encoding: &str,
+    sync_kind: u8,
+) -> std::io::Result<(EditorTestHarness, std::path::PathBuf)> {
+    let log_file = temp_dir.join("lsp_sync_log.txt");
+    let test_file = temp_dir.join("test.rs");
+    std::fs::write(&test_file, CONTENT)?;
+
+    let mut config = fresh::config::Config::default();
+    config.lsp.insert(
+        "rust".to_string(),
+        fresh::services::lsp::LspServerConfig {
+            command: FakeLspServer::sync_log_script_path()
+                .to_string_lossy()
+                .to_string(),
+            args: vec![
+                log_file.to_string_lossy().to_string(),
+                encoding.to_string(),
+                sync_kind.to_string(),
+            ],
+            enabled: true,
+            auto_start: true,
+            process_limits: fresh::services::process_limits::ProcessLimits::default(),
+            initialization_options: None,
+            name: None,
+            primary: false,
+        }
+        .into(),
+    );
+
+    let mut harness =
+        EditorTestHarness::with_config_and_working_dir(120, 30, config, temp_dir.to_path_buf())?;
+    harness.open_file(&test_file)?;
+    harness.render()?;
+
+    harness.wait_until(|h| {
+        let log = std::fs::read_to_string(&log_file).unwrap_or_default();
+        h.editor().is_lsp_server_ready("rust") && log.contains("textDocument/didOpen")
+    })?;
+    Ok((harness, log_file))
+}
+
+/// The content changes of each didChange logged so far
+fn did_changes(log_file: &Path) -> Vec<Vec<serde_json::Value>> {
+    std::fs::read_to_string(log_file)
+        .unwrap_or_default()
+        .lines()
+        .filter_map(|line| serde_json::from_str::<serde_json::Value>(line).ok())
+        .filter(|msg| msg["method"] == "textDocument/didChange")
+        .map(|msg| {
+            msg["params"]["contentChanges"]
+                .as_array()
+                .cloned()
+                .unwrap_or_default()
+        })
+        .collect()
+}
+
+/// The (start line, start character, end line, end character) and text of a
+/// range change
+fn range_of(change: &serde_json::Value) -> ((u64, u64, u64, u64), &str) {
+    let range = &change["range"];
+    let n = |value: &serde_json::Value| value.as_u64().expect("range position");
+    (
+        (
+            n(&range["start"]["line"]),
+            n(&range["start"]["character"]),
+            n(&range["end"]["line"]),
+            n(&range["end"]["character"]),
+        ),
+        change["text"].as_str().expect("change text"),
+    )
+}
+
+/// Type after the emoji, delete it again and type on the next line, then
+/// return the changes the server got for each edit
+fn edit_and_collect_changes(
+    harness: &mut EditorTestHarness,
+    log_file: &Path,
+) -> std::io::Result<Vec<((u64, u64, u64, u64), String)>> {
+    harness.send_key(KeyCode::End, KeyModifiers::NONE)?;
+    harness.type_text("X")?;
+    harness.send_key(KeyCode::Backspace, KeyModifiers::NONE)?;
+    harness.send_key(KeyCode::Down, KeyModifiers::NONE)?;
+    harness.send_key(KeyCode::Home, KeyModifiers::NONE)?;
+    harness.type_text("Z")?;
+    harness.render()?;
+
+    harness.wait_until(|_| did_changes(log_file).len() >= 3)?;
+    Ok(did_changes(log_file)
+        .iter()
+        .flatten()
+        .map(|change| {
+            let (range, text) = range_of(change);
+            (range, text.to_string())
+        })
+        .collect())
+}
+
+/// A UTF-16 server counts the emoji as two units
+#[test]
+#[cfg_attr(target_os = "windows", ignore)] // Uses Bash-based fake LSP server
+fn test_incremental_changes_in_utf16() -> std::io::Result<()> {
+    let _fake_server = FakeLspServer::spawn_with_sync_log()?;
+    let temp_dir = tempfile::tempdir()?;
+    let (mut harness, log_file) = open_with_server(temp_dir.path(), "utf-16", 2)?;
+
+    let changes = edit_and_collect_changes(&mut harness, &log_file)?;
+    assert_eq!(
+        changes,
+        vec![
+            ((0, 4, 0, 4), "X".to_string()),
+            ((0, 4, 0, 5), String::new()),
+            ((1, 0, 1, 0), "Z".to_string()),
+        ]
+    );
+    assert_eq!(
+        harness.editor().active_state().buffer.to_string().unwrap(),
+        "a😀b\nZline\n"
+    );
+    Ok(())
+}
+
+/// A UTF-8 server gets byte columns, the emoji counting as four
+#[test]
+#[cfg_attr(target_os = "windows", ignore)] // Uses Bash-based fake LSP server
+fn test_incremental_changes_in_utf8() -> std::io::Result<()> {
+    let _fake_server = FakeLspServer::spawn_with_sync_log()?;
+    let temp_dir = tempfile::tempdir()?;
+    let (mut harness, log_file) = open_with_server(temp_dir.path(), "utf-8", 2)?;
+
+    let changes = edit_and_collect_changes(&mut harness, &log_file)?;
+    assert_eq!(
+        changes,
+        vec![
+            ((0, 6, 0, 6), "X".to_string()),
+            ((0, 6, 0, 7), String::new()),
+            ((1, 0, 1, 0), "Z".to_string()),
+        ]
+    );
+    Ok(())
+}
+
+/// A full-sync server gets the whole text once, after edits pause
+#[test]
+#[cfg_attr(target_os = "windows", ignore)] // Uses Bash-based fake LSP server
+fn test_full_sync_sends_whole_text_once_edits_pause() -> std::io::Result<()> {
+    let _fake_server = FakeLspServer::spawn_with_sync_log()?;
+    let temp_dir = tempfile::tempdir()?;
+    let (mut harness, log_file) = open_with_server(temp_dir.path(), "utf-16", 1)?;
+
+    harness.send_key(KeyCode::End, KeyModifiers::NONE)?;
+    harness.type_text("X")?;
+    // Not yet due: no time passed since the edit
+    harness.editor_mut().check_lsp_sync_timer();
+    harness.type_text("Y")?;
+
+    harness.advance_time(Duration::from_millis(100));
+    harness.editor_mut().check_lsp_sync_timer();
+    harness.wait_until(|_| !did_changes(&log_file).is_empty())?;
+
+    let changes = did_changes(&log_file);
+    assert_eq!(changes.len(), 1, "one didChange for both edits");
+    assert_eq!(changes[0].len(), 1);
+    assert!(changes[0][0]["range"].is_null());
+    assert_eq!(changes[0][0]["text"], "a😀bXY\nline\n");
+    Ok(())
+}
@@ -0,0 +1,315 @@
+//! E2E tests for a language with several LSP servers
+//!
+//! Both servers get the buffer; their diagnostics are kept side by side,
+//! completions and hovers are merged, and on-type formatting goes only to
+//! the server marked primary.
+
+use crate::common::harness::EditorTestHarness;
+use crossterm::event::{KeyCode, KeyModifiers};
+use fresh::services::lsp::{LspLanguageConfig, LspServerConfig};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tempfile::TempDir;
+
+/// Fake language server named by its first argument, writing the methods it
+/// gets to the file its second argument names
+///
+/// Every answer carries the server's name. `alpha` leaves the source of its
+/// diagnostics out; `beta` answers hovers on the second line after three
+/// seconds.
+#[cfg(unix)]
+fn write_fake_server(dir: &Path) -> PathBuf {
+    let script = r#"#!/bin/bash
+
+name="$1"
+log="$2"
+
+read_message() {
+    local content_length=0
+    while IFS=: read -r key value; do
+        key=$(echo "$key" | tr -d '\r\n')
+        value=$(echo "$value" | tr -d '\r\n ')
+        if [ "$key" = "Content-Length" ]; then
+            content_length=$value
+        fi
+        if [ -z "$key" ]; then
+            break
+        fi
+    done
+
+    if [ $content_length -gt 0 ]; then
+        dd bs=1 count=$content_length 2>/dev/null
+    fi
+}
+
+send_message() {
+    local message="$1"
+    local length=${#message}
+    # The body is left as is, so escapes in its JSON strings stay escapes
+    echo -en "Content-Length: $length\r\n\r\n"
+    echo -n "$message"
+}
+
+if [ "$name" = "beta" ]; then
+    source_field=',"source":"betalint"'
+else
+    source_field=''
+fi
+
+while true; do
+    msg=$(read_message)
+
+    if [ -z "$msg" ]; then
+        break
+    fi
+
+    method=$(echo "$msg" | grep -o '"method":"[^"]*"' | cut -d'"' -f4)
+    msg_id=$(echo "$msg" | grep -o '"id":[0-9]*' | cut -d':' -f2)
+    echo "$method" >> "$log"
+
+    case "$method" in
+        "initialize")
+            send_message '{"jsonrpc":"2.0","id":'$msg_id',"result":{"capabilities":{"textDocumentSync":1,"completionProvider":{},"hoverProvider":true,"documentOnTypeFormattingProvider":{"firstTriggerCharacter":";"}}}}'
+            ;;
+        "textDocument/didOpen")
+            uri=$(echo "$msg" | grep -o '"uri":"[^"]*"' | head -1 | cut -d'"' -f4)
+            send_message '{"jsonrpc":"2.0","method":"textDocument/publishDiagnostics","params":{"uri":"'$uri'","diagnostics":[{"range":{"start":{"line":0,"character":0},"end":{"line":0,"character":2}},"severity":2,"message":"'$name' warning"'$source_field'}]}}'
+            ;;
+        "textDocument/completion")
+            send_message '{"jsonrpc":"2.0","id":'$msg_id',"result":[{"label":"shared_item","detail":"from '$name'"},{"label":"only_'$name'"}]}'
+            ;;
+        "textDocument/hover")
+            if [ "$name" = "beta" ] && echo "$msg" | grep -q '"line":1'; then
+                sleep 3
+            fi
+            send_message '{"jsonrpc":"2.0","id":'$msg_id',"result":{"contents":{"kind":"markdown","value":"'$name' hover text"}}}'
+            ;;
+        "textDocument/onTypeFormatting")
+            send_message '{"jsonrpc":"2.0","id":'$msg_id',"result":[{"range":{"start":{"line":0,"character":0},"end":{"line":0,"character":0}},"newText":"// '$name'\n"}]}'
+            ;;
+        "shutdown")
+            send_message '{"jsonrpc":"2.0","id":'$msg_id',"result":null}'
+            break
+            ;;
+        *)
+            if [ -n "$msg_id" ]; then
+                send_message '{"jsonrpc":"2.0","id":'$msg_id',"result":null}'
+            fi
+            ;;
+    esac
+done
+"#;
+
+    let script_path = dir.join("fake_multi_lsp.sh");
+    std::fs::write(&script_path, script).unwrap();
+    use std::os::unix::fs::PermissionsExt;
+    let mut perms = std::fs::metadata(&script_path).unwrap().permissions();
+    perms.set_mode(0o755);
+    std::fs::set_permissions(&script_path, perms).unwrap();
+    script_path
+}
+
+struct Project {
+    _temp_dir: TempDir,
+    dir: PathBuf,
+    harness: EditorTestHarness,
+}
+
+impl Project {
+    /// Methods the server `name` got so far
+    fn log(&self, name: &str) -> Vec<String> {
+        std::fs::read_to_string(self.dir.join(format!("{name}.log")))
+            .unwrap_or_default()
+            .lines()
+            .map(str::to_string)
+            .collect()
+    }
+}
+
+/// `main.rs` open, with servers `alpha` and `beta` for Rust, `beta` the
+/// primary one, both of which published their diagnostics
+fn open_with_two_servers() -> Project {
+    let temp_dir = TempDir::new().unwrap();
+    let dir = temp_dir.path().canonicalize().unwrap();
+    std::fs::write(dir.join("main.rs"), "fn main() {\n    let x = 1\n}\n").unwrap();
+
+    #[cfg(unix)]
+    let script_path = write_fake_server(&dir);
+    #[cfg(not(unix))]
+    let script_path = dir.join("unused");
+
+    let server = |name: &str, primary: bool| LspServerConfig {
+        command: script_path.to_string_lossy().to_string(),
+        args: vec![
+            name.to_string(),
+            dir.join(format!("{name}.log"))
+                .to_string_lossy()
+                .to_string(),
+        ],
+        enabled: true,
+        auto_start: true,
+        process_limits: fresh::services::process_limits::ProcessLimits::default(),
+        initialization_options: None,
+        name: Some(name.to_string()),
+        primary,
+    };
+    let mut config = fresh::config::Config::default();
+    config.lsp.insert(
+        "rust".to_string(),
+        LspLanguageConfig::Multi(vec![server("alpha", false), server("beta", true)]),
+    );
+    config.languages.get_mut("rust").unwrap().on_type_formatting = true;
+
+    let mut harness =
+        EditorTestHarness::with_config_and_working_dir(100, 30, config, dir.clone()).unwrap();
+    harness.open_file(&dir.join("main.rs")).unwrap();
+    harness
+        .wait_until(|h| {
+            h.editor()
+                .get_stored_diagnostics()
+                .by_uri()
+                .values()
+                .map(Vec::len)
+                .sum::<usize>()
+                == 2
+        })
+        .unwrap();
+    Project {
+        _temp_dir: temp_dir,
+        dir,
+        harness,
+    }
+}
+
+/// Diagnostics of both servers are kept, the ones without a source tagged
+/// with their server's name
+#[test]
+#[cfg_attr(windows, ignore)] // Uses bash script for fake LSP server
+fn test_diagnostics_of_each_server_kept() {
+    let project = open_with_two_servers();
+
+    let mut diagnostics: Vec<(String, Option<String>)> = project
+        .harness
+        .editor()
+        .get_stored_diagnostics()
+        .by_uri()
+        .into_values()
+        .flatten()
+        .map(|diagnostic| (diagnostic.message, diagnostic.source))
+        .collect();
+    diagnostics.sort();
+    assert_eq!(
+        diagnostics,
+        [
+            ("alpha warning".to_string(), Some("alpha".to_string())),
+            ("beta warning".to_string(), Some("betalint".to_string())),
+        ]
+    );
+}
+
+/// Completions of both servers are offered, an item both offer once
+#[test]
+#[cfg_attr(windows, ignore)] // Uses bash script for fake LSP server
+fn test_completions_merged() {
+    let mut project = open_with_two_servers();
+    let harness = &mut project.harness;
+
+    // After the `{`, so no typed word narrows the list
+    harness.send_key(KeyCode::End, KeyModifiers::NONE).unwrap();
+    harness
+        .send_key(KeyCode::Char(' '), KeyModifiers::CONTROL)
+        .unwrap();
+    harness.wait_for_screen_contains("only_alpha").unwrap();
+
+    let screen = harness.screen_to_string();
+    assert!(screen.contains("only_beta"), "{screen}");
+    assert_eq!(screen.matches("shared_item").count(), 1, "{screen}");
+}
+
+/// A hover shows each server's text; one left waiting on a slow server
+/// shows what the others answered once its time is up
+#[test]
+#[cfg_attr(windows, ignore)] // Uses bash script for fake LSP server
+fn test_hovers_merged() {
+    let mut project = open_with_two_servers();
+
+    project
+        .harness
+        .send_key(KeyCode::Char('k'), KeyModifiers::ALT)
+        .unwrap();
+    project
+        .harness
+        .wait_for_screen_contains("alpha hover text")
+        .unwrap();
+    project.harness.assert_screen_contains("beta hover text");
+
+    project
+        .harness
+        .send_key(KeyCode::Esc, KeyModifiers::NONE)
+        .unwrap();
+    project
+        .harness
+        .send_key(KeyCode::Down, KeyModifiers::NONE)
+        .unwrap();
+    project
+        .harness
+        .send_key(KeyCode::Char('k'), KeyModifiers::ALT)
+        .unwrap();
+    // Give alpha time to answer; beta is still asleep
+    while project
+        .log("alpha")
+        .iter()
+        .filter(|method| *method == "textDocument/hover")
+        .count()
+        < 2
+    {
+        std::thread::sleep(Duration::from_millis(50));
+    }
+    std::thread::sleep(Duration::from_millis(300));
+    project.harness.process_async_and_render().unwrap();
+    project
+        .harness
+        .assert_screen_not_contains("alpha hover text");
+
+    project.harness.advance_time(Duration::from_secs(2));
+    assert!(project.harness.editor_mut().check_lsp_fanout_deadlines());
+    project.harness.render().unwrap();
+    project.harness.assert_screen_contains("alpha hover text");
+    project
+        .harness
+        .assert_screen_not_contains("beta hover text");
+}
+
+/// On-type formatting is asked of the primary server only
+#[test]
+#[cfg_attr(windows, ignore)] // Uses bash script for fake LSP server
+fn test_on_type_formatting_from_primary_only() {
+    let mut project = open_with_two_servers();
+
+    project
+        .harness
+        .send_key(KeyCode::Down, KeyModifiers::NONE)
+        .unwrap();
+    project
+        .harness
+        .send_key(KeyCode::End, KeyModifiers::NONE)
+        .unwrap();
+    project.harness.type_text(";").unwrap();
+    project
+        .harness
+        .wait_until(|h| {
+            h.get_buffer_content()
+                .is_some_and(|content| content.starts_with("// beta\n"))
+        })
+        .unwrap();
+
+    assert!(project
+        .log("beta")
+        .contains(&"textDocument/onTypeFormatting".to_string()));
+    assert!(!project
+        .log("alpha")
+        .contains(&"textDocument/onTypeFormatting".to_string()));
+    project
+        .harness
+        .assert_buffer_content("// beta\nfn main() {\n    let x = 1;\n}\n");
+}
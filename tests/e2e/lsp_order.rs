@@ -50,7 +50,10 @@ fn test_did_open_sent_before_hover() -> std::io::Result<()> {
             auto_start: true,
             process_limits: fresh::services::process_limits::ProcessLimits::default(),
             initialization_options: None,
-        },
+            name: None,
+            primary: false,
+        }
+        .into(),
     );
 
     // Create harness with config
@@ -0,0 +1,313 @@
+//! E2E tests for LSP rename across files
+//!
+//! Renames come back from the server as a WorkspaceEdit; edits to more than one
+//! file go through the pending edits review before anything is applied.
+
+use crate::common::harness::EditorTestHarness;
+use crossterm::event::{KeyCode, KeyModifiers};
+use lsp_types::{
+    DocumentChanges, OneOf, OptionalVersionedTextDocumentIdentifier, Position, Range,
+    TextDocumentEdit, TextEdit, Uri, WorkspaceEdit,
+};
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+const MAIN_RS: &str = "mod util;\n\nfn main() {\n    util::helper();\n}\n";
+const UTIL_RS: &str = "pub fn helper() {}\n\npub fn other() {\n    helper();\n}\n";
+
+/// A project with main.rs calling a function defined (and used) in util.rs
+fn setup() -> (TempDir, PathBuf, PathBuf) {
+    let temp_dir = TempDir::new().unwrap();
+    let project = temp_dir.path().canonicalize().unwrap();
+    let main = project.join("main.rs");
+    let util = project.join("util.rs");
+    std::fs::write(&main, MAIN_RS).unwrap();
+    std::fs::write(&util, UTIL_RS).unwrap();
+    (temp_dir, main, util)
+}
+
+fn file_uri(path: &Path) -> Uri {
+    url::Url::from_file_path(path)
+        .unwrap()
+        .as_str()
+        .parse::<Uri>()
+        .unwrap()
+}
+
+fn edit(line: u32, start: u32, end: u32, new_text: &str) -> TextEdit {
+    TextEdit {
+        range: Range {
+            start: Position {
+                line,
+                character: start,
+            },
+            end: Position {
+                line,
+                character: end,
+            },
+        },
+        new_text: new_text.to_string(),
+    }
+}
+
+/// Rename of `helper` in main.rs and util.rs, as a server would send it
+fn two_file_edit(main: &Path, util: &Path, new_name: &str) -> WorkspaceEdit {
+    let document_edit = |path: &Path, edits: Vec<TextEdit>| TextDocumentEdit {
+        text_document: OptionalVersionedTextDocumentIdentifier {
+            uri: file_uri(path),
+            version: None,
+        },
+        edits: edits.into_iter().map(OneOf::Left).collect(),
+    };
+    WorkspaceEdit {
+        document_changes: Some(DocumentChanges::Edits(vec![
+            document_edit(main, vec![edit(3, 10, 16, new_name)]),
+            document_edit(
+                util,
+                vec![edit(0, 7, 13, new_name), edit(3, 4, 10, new_name)],
+            ),
+        ])),
+        ..Default::default()
+    }
+}
+
+/// Fake language server that supports prepareRename and renames `helper`
+/// in main.rs and util.rs
+#[cfg(unix)]
+fn write_fake_server(dir: &Path, main: &Path, util: &Path) -> PathBuf {
+    let script = r#"#!/bin/bash
+
+read_message() {
+    local content_length=0
+    while IFS=: read -r key value; do
+        key=$(echo "$key" | tr -d '\r\n')
+        value=$(echo "$value" | tr -d '\r\n ')
+        if [ "$key" = "Content-Length" ]; then
+            content_length=$value
+        fi
+        if [ -z "$key" ]; then
+            break
+        fi
+    done
+
+    if [ $content_length -gt 0 ]; then
+        dd bs=1 count=$content_length 2>/dev/null
+    fi
+}
+
+send_message() {
+    local message="$1"
+    local length=${#message}
+    echo -en "Content-Length: $length\r\n\r\n$message"
+}
+
+while true; do
+    msg=$(read_message)
+
+    if [ -z "$msg" ]; then
+        break
+    fi
+
+    method=$(echo "$msg" | grep -o '"method":"[^"]*"' | cut -d'"' -f4)
+    msg_id=$(echo "$msg" | grep -o '"id":[0-9]*' | cut -d':' -f2)
+
+    case "$method" in
+        "initialize")
+            send_message '{"jsonrpc":"2.0","id":'$msg_id',"result":{"capabilities":{"textDocumentSync":1,"renameProvider":{"prepareProvider":true}}}}'
+            ;;
+        "textDocument/prepareRename")
+            send_message '{"jsonrpc":"2.0","id":'$msg_id',"result":{"range":{"start":{"line":3,"character":10},"end":{"line":3,"character":16}},"placeholder":"helper"}}'
+            ;;
+        "textDocument/rename")
+            name=$(echo "$msg" | grep -o '"newName":"[^"]*"' | cut -d'"' -f4)
+            send_message '{"jsonrpc":"2.0","id":'$msg_id',"result":{"documentChanges":[{"textDocument":{"uri":"MAIN_URI","version":0},"edits":[{"range":{"start":{"line":3,"character":10},"end":{"line":3,"character":16}},"newText":"'$name'"}]},{"textDocument":{"uri":"UTIL_URI","version":null},"edits":[{"range":{"start":{"line":0,"character":7},"end":{"line":0,"character":13}},"newText":"'$name'"},{"range":{"start":{"line":3,"character":4},"end":{"line":3,"character":10}},"newText":"'$name'"}]}]}}'
+            ;;
+        "shutdown")
+            send_message '{"jsonrpc":"2.0","id":'$msg_id',"result":null}'
+            break
+            ;;
+        *)
+            # Answer any other request (diagnostics, inlay hints, ...) with nothing
+            if [ -n "$msg_id" ]; then
+                send_message '{"jsonrpc":"2.0","id":'$msg_id',"result":null}'
+            fi
+            ;;
+    esac
+done
+"#
+    .replace("MAIN_URI", file_uri(main).as_str())
+    .replace("UTIL_URI", file_uri(util).as_str());
+
+    let script_path = dir.join("fake_rename_lsp.sh");
+    std::fs::write(&script_path, script).unwrap();
+    use std::os::unix::fs::PermissionsExt;
+    let mut perms = std::fs::metadata(&script_path).unwrap().permissions();
+    perms.set_mode(0o755);
+    std::fs::set_permissions(&script_path, perms).unwrap();
+    script_path
+}
+
+/// Renaming through a language server edits both files after review, one undo
+/// step per buffer
+#[test]
+#[cfg_attr(windows, ignore)] // Uses bash script for fake LSP server
+fn test_lsp_rename_across_files() {
+    let (temp_dir, main, util) = setup();
+    let scripts = TempDir::new().unwrap();
+    #[cfg(unix)]
+    let script_path = write_fake_server(scripts.path(), &main, &util);
+    #[cfg(not(unix))]
+    let script_path = scripts.path().join("unused");
+
+    let mut config = fresh::config::Config::default();
+    config.lsp.insert(
+        "rust".to_string(),
+        fresh::services::lsp::LspServerConfig {
+            command: script_path.to_string_lossy().to_string(),
+            args: vec![],
+            enabled: true,
+            auto_start: true,
+            process_limits: fresh::services::process_limits::ProcessLimits::default(),
+            initialization_options: None,
+            name: None,
+            primary: false,
+        }
+        .into(),
+    );
+    let mut harness = EditorTestHarness::with_config_and_working_dir(
+        100,
+        30,
+        config,
+        temp_dir.path().canonicalize().unwrap(),
+    )
+    .unwrap();
+
+    harness.open_file(&main).unwrap();
+    harness
+        .wait_until(|h| h.editor().is_lsp_server_ready("rust"))
+        .unwrap();
+
+    // Cursor on `helper` in `util::helper();`
+    harness
+        .send_key(KeyCode::Home, KeyModifiers::CONTROL)
+        .unwrap();
+    for _ in 0..3 {
+        harness.send_key(KeyCode::Down, KeyModifiers::NONE).unwrap();
+    }
+    harness.send_key(KeyCode::End, KeyModifiers::NONE).unwrap();
+    for _ in 0..4 {
+        harness.send_key(KeyCode::Left, KeyModifiers::NONE).unwrap();
+    }
+
+    // The prompt is pre-filled with the server's placeholder
+    harness.send_key(KeyCode::F(2), KeyModifiers::NONE).unwrap();
+    harness.wait_for_prompt().unwrap();
+    harness.assert_screen_contains("Rename to: helper");
+    for _ in 0.."helper".len() {
+        harness
+            .send_key(KeyCode::Backspace, KeyModifiers::NONE)
+            .unwrap();
+    }
+    harness.type_text("assist").unwrap();
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+
+    // Two files: the edits are reviewed first
+    harness
+        .wait_for_screen_contains("Pending edits: Rename to 'assist'")
+        .unwrap();
+    harness.assert_screen_contains("3/3 edits in 2/2 files");
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    harness.render().unwrap();
+    harness.assert_screen_contains("Renamed in 3 places across 2 files");
+
+    harness.open_file(&util).unwrap();
+    harness.assert_buffer_content("pub fn assist() {}\n\npub fn other() {\n    assist();\n}\n");
+    harness
+        .send_key(KeyCode::Char('z'), KeyModifiers::CONTROL)
+        .unwrap();
+    harness.assert_buffer_content(UTIL_RS);
+
+    harness.open_file(&main).unwrap();
+    harness.assert_buffer_content("mod util;\n\nfn main() {\n    util::assist();\n}\n");
+}
+
+/// Nothing is applied if any file changed after the rename was computed
+#[test]
+fn test_lsp_rename_not_applied_when_file_changed() {
+    let (temp_dir, main, util) = setup();
+    let mut harness =
+        EditorTestHarness::with_working_dir(100, 30, temp_dir.path().canonicalize().unwrap())
+            .unwrap();
+    harness.open_file(&main).unwrap();
+
+    harness
+        .editor_mut()
+        .handle_rename_response(0, Ok(two_file_edit(&main, &util, "assist")))
+        .unwrap();
+    harness.render().unwrap();
+    harness.assert_screen_contains("3/3 edits in 2/2 files");
+
+    // util.rs changes on disk while the review is open
+    std::fs::write(&util, format!("// header\n{}", UTIL_RS)).unwrap();
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    harness.render().unwrap();
+    harness.assert_screen_contains("Rename not applied:");
+
+    harness.open_file(&main).unwrap();
+    harness.assert_buffer_content(MAIN_RS);
+}
+
+/// Edits outside the working directory are listed but left unchecked
+#[test]
+fn test_lsp_rename_outside_working_dir_needs_confirmation() {
+    let (temp_dir, main, _util) = setup();
+    let elsewhere = TempDir::new().unwrap();
+    let outside = elsewhere.path().canonicalize().unwrap().join("util.rs");
+    std::fs::write(&outside, UTIL_RS).unwrap();
+
+    let mut harness =
+        EditorTestHarness::with_working_dir(100, 30, temp_dir.path().canonicalize().unwrap())
+            .unwrap();
+    harness.open_file(&main).unwrap();
+
+    harness
+        .editor_mut()
+        .handle_rename_response(0, Ok(two_file_edit(&main, &outside, "assist")))
+        .unwrap();
+    harness.render().unwrap();
+    harness.assert_screen_contains("1/3 edits in 1/2 files");
+    harness.assert_screen_contains("2 edits outside the working directory");
+
+    // Confirming applies only the checked edit
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    harness.render().unwrap();
+    harness.assert_screen_contains("Renamed in 1 places");
+    harness.assert_buffer_content("mod util;\n\nfn main() {\n    util::assist();\n}\n");
+    assert_eq!(std::fs::read_to_string(&outside).unwrap(), UTIL_RS);
+}
+
+/// A server answering with no edits is reported instead of silently ignored
+#[test]
+fn test_lsp_rename_without_edits_is_reported() {
+    let (temp_dir, main, _util) = setup();
+    let mut harness =
+        EditorTestHarness::with_working_dir(100, 30, temp_dir.path().canonicalize().unwrap())
+            .unwrap();
+    harness.open_file(&main).unwrap();
+
+    harness
+        .editor_mut()
+        .handle_rename_response(0, Ok(WorkspaceEdit::default()))
+        .unwrap();
+    harness.render().unwrap();
+    harness.assert_screen_contains("returned no edits");
+    harness.assert_buffer_content(MAIN_RS);
+}
@@ -1,5 +1,9 @@
 use crate::common::harness::EditorTestHarness;
 use crossterm::event::{KeyCode, KeyModifiers};
+use fresh::config::Config;
+use fresh::services::plugins::api::PluginCommand;
+use fresh::view::margin::GutterAlignment;
+use fresh::view::theme::DecorationColor;
 use tempfile::TempDir;
 
 /// Test that line numbers are rendered correctly with the margin system
@@ -524,3 +528,105 @@ fn test_line_numbers_update_with_navigation_keys() {
     harness.render().unwrap();
     harness.assert_screen_contains("   1 │");
 }
+
+fn register_coverage_column(harness: &mut EditorTestHarness) {
+    let editor = harness.editor_mut();
+    editor
+        .handle_plugin_command(PluginCommand::RegisterGutterColumn {
+            name: "coverage".to_string(),
+            width: 2,
+            alignment: GutterAlignment::Right,
+        })
+        .unwrap();
+    let buffer_id = editor.active_buffer();
+    editor
+        .handle_plugin_command(PluginCommand::SetLineIndicator {
+            buffer_id,
+            line: 1,
+            namespace: "coverage".to_string(),
+            symbol: "▌".to_string(),
+            color: DecorationColor::Rgb(0, 200, 0),
+            priority: 0,
+            column: Some("coverage".to_string()),
+        })
+        .unwrap();
+}
+
+/// Screen row showing `text`
+fn row_of(harness: &EditorTestHarness, text: &str) -> u16 {
+    harness
+        .screen_to_string()
+        .lines()
+        .position(|line| line.contains(text))
+        .unwrap_or_else(|| panic!("{text:?} not on screen")) as u16
+}
+
+/// A plugin column is drawn left of the built-in columns and shows only the
+/// indicators set for it
+#[test]
+fn test_plugin_gutter_column_rendering() {
+    let mut harness = EditorTestHarness::new(80, 24).unwrap();
+    let _fixture = harness
+        .load_buffer_from_text("alpha\nbeta\ngamma\n")
+        .unwrap();
+    register_coverage_column(&mut harness);
+    harness.render().unwrap();
+
+    // [coverage (2)][indicator (1)][line number (4)][separator]
+    harness.assert_screen_contains("      1 │ alpha");
+    harness.assert_screen_contains(" ▌    2 │ beta");
+    harness.assert_screen_contains("      3 │ gamma");
+
+    harness
+        .editor_mut()
+        .handle_plugin_command(PluginCommand::UnregisterGutterColumn {
+            name: "coverage".to_string(),
+        })
+        .unwrap();
+    harness.render().unwrap();
+    harness.assert_screen_contains("    2 │ beta");
+    harness.assert_screen_not_contains("▌");
+}
+
+/// `editor.gutter_columns` orders the columns and leaves out unlisted
+/// built-in ones; `editor.gutter_column_widths` resizes them
+#[test]
+fn test_gutter_columns_config() {
+    let mut config = Config::default();
+    config.editor.gutter_columns = vec!["line_numbers".to_string(), "coverage".to_string()];
+    config
+        .editor
+        .gutter_column_widths
+        .insert("coverage".to_string(), 1);
+    let mut harness = EditorTestHarness::with_config(80, 24, config).unwrap();
+    let _fixture = harness
+        .load_buffer_from_text("alpha\nbeta\ngamma\n")
+        .unwrap();
+    register_coverage_column(&mut harness);
+    harness.render().unwrap();
+
+    // [line number (4)][coverage (1)][separator]
+    harness.assert_screen_contains("   1  │ alpha");
+    harness.assert_screen_contains("   2▌ │ beta");
+}
+
+/// Clicking a plugin column moves the cursor to the clicked line like the
+/// rest of the gutter
+#[test]
+fn test_click_in_plugin_gutter_column() {
+    let mut harness = EditorTestHarness::new(80, 24).unwrap();
+    let _fixture = harness
+        .load_buffer_from_text("alpha\nbeta\ngamma\n")
+        .unwrap();
+    register_coverage_column(&mut harness);
+    harness.render().unwrap();
+
+    let row = row_of(&harness, "gamma");
+    harness.mouse_click(1, row).unwrap();
+    assert_eq!(harness.cursor_position(), "alpha\nbeta\n".len());
+    assert_eq!(
+        harness.editor().gutter_layout()[0].name(),
+        "coverage",
+        "plugin column should come first"
+    );
+}
@@ -509,12 +509,8 @@ fn test_copy_with_formatting_submenu_activates_on_enter() {
         .unwrap();
     harness.render().unwrap();
 
-    // Verify text is selected (status bar should show selection)
-    let screen = harness.screen_to_string();
-    assert!(
-        screen.contains("Hello World"),
-        "Text should be visible in the editor"
-    );
+    // Verify text is selected (its space is drawn as a whitespace marker)
+    assert_eq!(harness.get_selected_text(), "Hello World");
 
     // Open Edit menu
     harness
@@ -567,9 +563,10 @@ fn test_copy_with_formatting_submenu_activates_on_enter() {
         "Menu should close after activating copy action. Screen:\n{}",
         screen_after
     );
-    // The editor content should still be visible
+    // The editor content should still be visible, its selected space drawn
+    // as a whitespace marker
     assert!(
-        screen_after.contains("Hello World"),
+        screen_after.contains("Hello") && screen_after.contains("World"),
         "Editor content should still be visible after copy. Screen:\n{}",
         screen_after
     );
@@ -1,28 +1,64 @@
+pub mod ambiguous_width;
+pub mod anchored_popup;
+pub mod ansi_colors;
 pub mod ansi_cursor;
+pub mod ascii_only;
+pub mod async_queue;
+pub mod auto_completion;
 pub mod auto_indent;
 pub mod auto_revert;
 pub mod basic;
+pub mod batch_code_actions;
 pub mod binary_file;
+pub mod buffer_compaction;
+pub mod buffer_compare;
 pub mod buffer_lifecycle;
+pub mod buffer_pins;
 pub mod buffer_settings_commands;
 pub mod case_conversion;
+pub mod column_align;
 pub mod command_palette;
+pub mod command_trust;
 pub mod crash_repro;
 pub mod crlf_rendering;
+pub mod data_path;
+pub mod decoration_colors;
+pub mod dir_compare;
+pub mod distraction_free;
+pub mod dry_run;
+pub mod editor_log;
 pub mod document_model;
 pub mod emacs_actions;
+pub mod event_replay;
+pub mod explorer_context_menu;
 pub mod explorer_menu;
+pub mod explorer_refresh;
 pub mod file_browser;
 pub mod file_explorer;
+pub mod file_explorer_details;
+pub mod file_explorer_large_dirs;
+pub mod file_loading;
 pub mod file_permissions;
+pub mod file_saving;
+pub mod gitattributes;
 pub mod indent_dedent;
+pub mod inline_blame;
+pub mod input_layers;
+pub mod insert_content;
+pub mod invalid_utf8;
+pub mod keyboard_shortcuts;
 pub mod large_file_mode;
 pub mod lifecycle;
+pub mod line_layout_cache;
 pub mod line_wrapping;
 pub mod live_grep;
 pub mod locale;
+pub mod long_messages;
 pub mod lsp;
+pub mod lsp_document_sync;
+pub mod lsp_multi_server;
 pub mod lsp_order;
+pub mod lsp_rename;
 pub mod margin;
 pub mod markdown_compose;
 pub mod menu_bar;
@@ -32,29 +68,48 @@ pub mod movement;
 pub mod multi_file_opening;
 pub mod multibyte_characters;
 pub mod multicursor;
+pub mod named_layouts;
+pub mod notifications;
 pub mod on_save_actions;
 pub mod open_folder;
+pub mod open_path;
 pub mod paste;
+pub mod pending_edits;
+pub mod plugin_buffer_lifecycle;
+pub mod plugin_init_failure;
+pub mod plugin_text_sanitize;
 #[cfg(feature = "plugins")]
 pub mod plugins;
 pub mod position_history;
 pub mod position_history_bugs;
 pub mod position_history_debug;
 pub mod position_history_truncate_debug;
+pub mod project_switch;
 pub mod prompt;
 pub mod prompt_editing;
+pub mod prose_motions;
+pub mod protected_files;
 pub mod recovery;
+pub mod remote_files;
+pub mod rename_in_buffer;
 pub mod rendering;
+pub mod rulers;
+pub mod save_actions;
 pub mod scroll_clearing;
 pub mod scrolling;
 pub mod search;
 pub mod selection;
+pub mod selection_stats;
 pub mod session;
+pub mod session_ownership;
 pub mod settings;
+pub mod settings_archive;
 pub mod settings_paste;
 pub mod shell_command;
 pub mod slow_filesystem;
 pub mod smart_editing;
+pub mod split_lock;
+pub mod split_search;
 pub mod split_tabs;
 pub mod split_view;
 pub mod split_view_expectations;
@@ -65,15 +120,25 @@ pub mod tab_indent_selection;
 pub mod tab_scrolling;
 pub mod terminal;
 pub mod terminal_close;
+pub mod terminal_effects;
 pub mod terminal_resize;
 pub mod test_scrollbar_keybinds_cursor;
+pub mod text_transforms;
 pub mod theme;
+pub mod todos;
+pub mod undo_labels;
+pub mod undo_plugin_edits;
 pub mod undo_redo;
 pub mod unicode_cursor;
 pub mod unicode_prompt_bugs;
 pub mod update_notification;
+pub mod usage_stats;
+pub mod validation;
 #[cfg(feature = "plugins")]
 pub mod vi_mode;
 pub mod virtual_lines;
 pub mod visual_regression;
 pub mod warning_indicators;
+pub mod winbar;
+pub mod word_completion;
+pub mod workspace_diagnostics;
@@ -1554,6 +1554,36 @@ fn test_force_check_mouse_hover() {
     );
 }
 
+/// Test that the hover request waits out the configured delay on the editor's clock
+#[test]
+fn test_mouse_hover_waits_for_delay() {
+    let mut harness = EditorTestHarness::new(80, 24).unwrap();
+    let delay = std::time::Duration::from_millis(harness.config().editor.mouse_hover_delay_ms);
+
+    let content = "let variable = 123;\n";
+    let _fixture = harness.load_buffer_from_text(content).unwrap();
+    harness.render().unwrap();
+
+    let (content_first_row, _) = harness.content_area_rows();
+    harness.mouse_move(12, content_first_row as u16).unwrap();
+
+    harness.advance_time(delay - std::time::Duration::from_millis(1));
+    assert!(
+        !harness.editor_mut().check_mouse_hover_timer(),
+        "Hover should not be requested before the delay"
+    );
+
+    harness.advance_time(std::time::Duration::from_millis(1));
+    assert!(
+        harness.editor_mut().check_mouse_hover_timer(),
+        "Hover should be requested once the delay has passed"
+    );
+    assert!(
+        !harness.editor_mut().check_mouse_hover_timer(),
+        "Hover should be requested only once per position"
+    );
+}
+
 /// Test that hover state is preserved when staying at same position
 #[test]
 fn test_mouse_hover_same_position_preserves_state() {
@@ -1743,3 +1773,111 @@ fn test_double_click_requires_same_position() {
         "Double-click at same position SHOULD select a word, but got empty selection"
     );
 }
+
+/// Harness with line wrapping off, for horizontal scroll tests
+fn horizontal_scroll_harness(horizontal_scroll_columns: usize) -> EditorTestHarness {
+    let mut config = fresh::config::Config::default();
+    config.editor.line_wrap = false;
+    config.mouse.horizontal_scroll_columns = horizontal_scroll_columns;
+    EditorTestHarness::with_config(80, 24, config).unwrap()
+}
+
+/// Test horizontal wheel scrolling moves the view by the configured amount
+/// and clamps at both ends without jitter
+#[test]
+fn test_mouse_horizontal_scroll_clamps() {
+    let mut harness = horizontal_scroll_harness(4);
+    let long_line = format!("START{}END", "x".repeat(200));
+    harness
+        .load_buffer_from_text(&format!("{long_line}\nshort\n"))
+        .unwrap();
+    harness.render().unwrap();
+    assert_eq!(harness.editor().active_viewport().left_column, 0);
+
+    // Already at the start: scrolling left stays put
+    harness.mouse_scroll_left(40, 5).unwrap();
+    assert_eq!(harness.editor().active_viewport().left_column, 0);
+
+    harness.mouse_scroll_right(40, 5).unwrap();
+    assert_eq!(harness.editor().active_viewport().left_column, 4);
+    harness.assert_screen_not_contains("START");
+
+    // Scroll far past the end: clamped so the end of the line stays visible
+    for _ in 0..100 {
+        harness.mouse_scroll_right(40, 5).unwrap();
+    }
+    let clamped = harness.editor().active_viewport().left_column;
+    assert!(clamped > 4 && clamped < long_line.len());
+    harness.assert_screen_contains("END");
+
+    // More scrolling at the edge doesn't move the view
+    for _ in 0..3 {
+        harness.mouse_scroll_right(40, 5).unwrap();
+        assert_eq!(harness.editor().active_viewport().left_column, clamped);
+    }
+
+    for _ in 0..100 {
+        harness.mouse_scroll_left(40, 5).unwrap();
+    }
+    assert_eq!(harness.editor().active_viewport().left_column, 0);
+    harness.assert_screen_contains("START");
+}
+
+/// Test horizontal scrolling is disabled when lines wrap
+#[test]
+fn test_mouse_horizontal_scroll_ignored_with_line_wrap() {
+    let mut config = fresh::config::Config::default();
+    config.editor.line_wrap = true;
+    let mut harness = EditorTestHarness::with_config(80, 24, config).unwrap();
+    harness
+        .load_buffer_from_text(&format!("{}\n", "x".repeat(300)))
+        .unwrap();
+    harness.render().unwrap();
+
+    harness.mouse_scroll_right(40, 5).unwrap();
+    assert_eq!(harness.editor().active_viewport().left_column, 0);
+}
+
+/// Test wheel scrolling applies to the split under the mouse, not the focused one
+#[test]
+fn test_mouse_scroll_targets_hovered_split() {
+    let mut harness = horizontal_scroll_harness(4);
+    let content: String = (1..=100)
+        .map(|i| format!("Line {i} {}\n", "y".repeat(100)))
+        .collect();
+    harness.load_buffer_from_text(&content).unwrap();
+    harness.render().unwrap();
+
+    // Create vertical split via command palette; the new right split is focused
+    harness
+        .send_key(KeyCode::Char('p'), KeyModifiers::CONTROL)
+        .unwrap();
+    harness.type_text("split vert").unwrap();
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    harness.render().unwrap();
+
+    // Scroll over the left (unfocused) split
+    harness.mouse_scroll_down(10, 5).unwrap();
+    harness.mouse_scroll_right(10, 5).unwrap();
+
+    // The focused split didn't move
+    let viewport = harness.editor().active_viewport();
+    assert_eq!(viewport.top_byte, 0);
+    assert_eq!(viewport.left_column, 0);
+
+    // The left split did: its first text row, below the winbar, no longer
+    // shows line 1
+    let first_row = harness.get_screen_row(3);
+    let left: String = first_row.chars().take(40).collect();
+    let right: String = first_row.chars().skip(40).collect();
+    assert!(
+        !left.contains("Line 1 "),
+        "left split should scroll: {first_row}"
+    );
+    assert!(
+        right.contains("Line 1 "),
+        "right split should not scroll: {first_row}"
+    );
+}
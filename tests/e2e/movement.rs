@@ -650,3 +650,184 @@ fn test_movement_through_multiple_empty_lines() {
         "Should be somewhere on Line 6, got position {final_pos}"
     );
 }
+
+/// Open `content` in a fresh harness for goal column tests
+fn harness_with_content(content: &str) -> (EditorTestHarness, TempDir) {
+    let temp_dir = TempDir::new().unwrap();
+    let file_path = temp_dir.path().join("test.txt");
+    std::fs::write(&file_path, content).unwrap();
+
+    let mut harness = EditorTestHarness::new(80, 24).unwrap();
+    harness.open_file(&file_path).unwrap();
+    (harness, temp_dir)
+}
+
+/// Moving through an empty line returns to the original column on the next long line
+#[test]
+fn test_goal_column_restored_after_empty_line() {
+    // Line 1: positions 0-9, empty line at 11, line 3 starts at 12
+    let (mut harness, _temp_dir) = harness_with_content("0123456789\n\n0123456789\n");
+
+    for _ in 0..7 {
+        harness
+            .send_key(KeyCode::Right, KeyModifiers::NONE)
+            .unwrap();
+    }
+    assert_eq!(harness.cursor_position(), 7);
+
+    harness.send_key(KeyCode::Down, KeyModifiers::NONE).unwrap();
+    assert_eq!(harness.cursor_position(), 11, "Should clamp to empty line");
+
+    harness.send_key(KeyCode::Down, KeyModifiers::NONE).unwrap();
+    assert_eq!(
+        harness.cursor_position(),
+        19,
+        "Should return to column 7 on line 3"
+    );
+
+    harness.send_key(KeyCode::Up, KeyModifiers::NONE).unwrap();
+    harness.send_key(KeyCode::Up, KeyModifiers::NONE).unwrap();
+    assert_eq!(
+        harness.cursor_position(),
+        7,
+        "Should return to column 7 on line 1"
+    );
+}
+
+/// The goal column is visual, so a tab and the equivalent spaces line up
+#[test]
+fn test_goal_column_is_tab_aware() {
+    // Tab size is 4: "\tab|" and "    ab|" both put the cursor at visual column 6
+    let (mut harness, _temp_dir) = harness_with_content("\tabcdef\n\n    abcdef\n");
+
+    for _ in 0..3 {
+        harness
+            .send_key(KeyCode::Right, KeyModifiers::NONE)
+            .unwrap();
+    }
+    assert_eq!(harness.cursor_position(), 3);
+    harness.render().unwrap();
+    let (start_x, _) = harness.screen_cursor_position();
+
+    harness.send_key(KeyCode::Down, KeyModifiers::NONE).unwrap();
+    assert_eq!(harness.cursor_position(), 8, "Should clamp to empty line");
+
+    harness.send_key(KeyCode::Down, KeyModifiers::NONE).unwrap();
+    assert_eq!(
+        harness.cursor_position(),
+        15,
+        "Should land after \"ab\" on the space-indented line"
+    );
+    harness.render().unwrap();
+    let (end_x, _) = harness.screen_cursor_position();
+    assert_eq!(
+        start_x, end_x,
+        "Cursor should stay in the same screen column"
+    );
+
+    harness.send_key(KeyCode::Up, KeyModifiers::NONE).unwrap();
+    harness.send_key(KeyCode::Up, KeyModifiers::NONE).unwrap();
+    assert_eq!(harness.cursor_position(), 3);
+}
+
+/// Wide characters count by display width when restoring the goal column
+#[test]
+fn test_goal_column_with_cjk_text() {
+    // "你好世" is 9 bytes and 6 columns wide; line 3 starts at byte 14
+    let (mut harness, _temp_dir) = harness_with_content("你好世界\n\nab你好cdef\n");
+
+    for _ in 0..3 {
+        harness
+            .send_key(KeyCode::Right, KeyModifiers::NONE)
+            .unwrap();
+    }
+    assert_eq!(harness.cursor_position(), 9);
+
+    harness.send_key(KeyCode::Down, KeyModifiers::NONE).unwrap();
+    assert_eq!(harness.cursor_position(), 13, "Should clamp to empty line");
+
+    // Column 6 on "ab你好cdef" is 'c', 8 bytes into the line
+    harness.send_key(KeyCode::Down, KeyModifiers::NONE).unwrap();
+    assert_eq!(harness.cursor_position(), 22);
+
+    harness.send_key(KeyCode::Up, KeyModifiers::NONE).unwrap();
+    harness.send_key(KeyCode::Up, KeyModifiers::NONE).unwrap();
+    assert_eq!(harness.cursor_position(), 9);
+}
+
+/// Typing on a short line replaces the remembered goal column
+#[test]
+fn test_goal_column_reset_by_editing() {
+    let (mut harness, _temp_dir) = harness_with_content("0123456789\n\n0123456789\n");
+
+    harness.send_key(KeyCode::End, KeyModifiers::NONE).unwrap();
+    harness.send_key(KeyCode::Down, KeyModifiers::NONE).unwrap();
+    harness.type_text("x").unwrap();
+    assert_eq!(harness.cursor_position(), 12);
+
+    harness.send_key(KeyCode::Down, KeyModifiers::NONE).unwrap();
+    assert_eq!(
+        harness.cursor_position(),
+        14,
+        "Should use the column after the edit, not the old goal column"
+    );
+}
+
+/// Page movement keeps the goal column across short lines
+#[test]
+fn test_goal_column_preserved_by_page_down() {
+    let mut content = String::from("0123456789\n\n");
+    for _ in 0..60 {
+        content.push_str("0123456789\n");
+    }
+    let (mut harness, _temp_dir) = harness_with_content(&content);
+
+    for _ in 0..5 {
+        harness
+            .send_key(KeyCode::Right, KeyModifiers::NONE)
+            .unwrap();
+    }
+    harness.send_key(KeyCode::Down, KeyModifiers::NONE).unwrap();
+    assert_eq!(harness.cursor_position(), 11, "Should clamp to empty line");
+
+    harness
+        .send_key(KeyCode::PageDown, KeyModifiers::NONE)
+        .unwrap();
+    let pos = harness.cursor_position();
+    let line_start = content[..pos].rfind('\n').map_or(0, |i| i + 1);
+    assert!(pos > 11, "PageDown should move past the empty line");
+    assert_eq!(pos - line_start, 5, "PageDown should restore column 5");
+}
+
+/// The goto-line prompt also takes `:byte N` and `:N%`
+#[test]
+fn test_goto_line_prompt_accepts_byte_and_percentage() {
+    let temp_dir = TempDir::new().unwrap();
+    let file_path = temp_dir.path().join("offsets.txt");
+    std::fs::write(&file_path, "0123456789\n".repeat(10)).unwrap();
+    let mut harness = EditorTestHarness::new(80, 24).unwrap();
+    harness.open_file(&file_path).unwrap();
+
+    fn goto(harness: &mut EditorTestHarness, input: &str) {
+        harness
+            .send_key(KeyCode::Char('g'), KeyModifiers::CONTROL)
+            .unwrap();
+        harness.type_text(input).unwrap();
+        harness
+            .send_key(KeyCode::Enter, KeyModifiers::NONE)
+            .unwrap();
+    }
+
+    goto(&mut harness, ":byte 0x2A");
+    assert_eq!(harness.cursor_position(), 42);
+
+    goto(&mut harness, ":byte 1000000");
+    assert_eq!(harness.cursor_position(), 110);
+
+    // 52% is byte 57, on the line starting at 55
+    goto(&mut harness, ":52%");
+    assert_eq!(harness.cursor_position(), 55);
+
+    goto(&mut harness, "3");
+    assert_eq!(harness.cursor_position(), 22);
+}
@@ -399,6 +399,39 @@ fn test_multi_cursor_delete_undo_atomic() {
     harness.assert_buffer_content("aaa\nbbb\nccc");
 }
 
+/// Test that cursors meeting after a multi-cursor edit merge, and that
+/// undo brings them back apart
+#[test]
+fn test_multi_cursor_merged_cursors_undo() {
+    use crossterm::event::{KeyCode, KeyModifiers};
+    let mut harness = EditorTestHarness::new(80, 24).unwrap();
+
+    harness.type_text("ab\n\ncd").unwrap();
+
+    // One cursor at the end of "ab", one on the empty line below it
+    harness
+        .send_key(KeyCode::Home, KeyModifiers::CONTROL)
+        .unwrap();
+    harness.send_key(KeyCode::End, KeyModifiers::NONE).unwrap();
+    harness.editor_mut().add_cursor_below();
+    assert_eq!(harness.editor().active_state().cursors.iter().count(), 2);
+
+    // Both backspaces leave their cursor at the same place
+    harness
+        .send_key(KeyCode::Backspace, KeyModifiers::NONE)
+        .unwrap();
+    harness.assert_buffer_content("a\ncd");
+    assert_eq!(harness.editor().active_state().cursors.iter().count(), 1);
+
+    harness
+        .send_key(KeyCode::Char('z'), KeyModifiers::CONTROL)
+        .unwrap();
+    harness.render().unwrap();
+
+    harness.assert_buffer_content("ab\n\ncd");
+    assert_eq!(harness.editor().active_state().cursors.iter().count(), 2);
+}
+
 /// Test that adding cursors can be undone
 #[test]
 fn test_add_cursor_undo() {
@@ -1295,3 +1328,165 @@ fn test_esc_returns_to_original_cursor_position() {
         "After pressing Esc, cursor should return to original position {original_position} but is at {final_position}"
     );
 }
+
+/// Screen position of the first occurrence of `text`
+fn screen_position_of(harness: &EditorTestHarness, text: &str) -> (u16, u16) {
+    harness
+        .screen_to_string()
+        .lines()
+        .enumerate()
+        .find_map(|(row, line)| {
+            line.find(text)
+                .map(|byte| (line[..byte].chars().count() as u16, row as u16))
+        })
+        .unwrap_or_else(|| panic!("{text:?} not on screen"))
+}
+
+fn cursor_positions(harness: &EditorTestHarness) -> Vec<usize> {
+    let mut positions = harness.editor().active_state().cursors.positions();
+    positions.sort();
+    positions
+}
+
+/// Ctrl+Click adds a cursor without moving the existing one, and typing
+/// then undoing works as with keyboard-added cursors
+#[test]
+fn test_ctrl_click_adds_cursor() {
+    use crossterm::event::{KeyCode, KeyModifiers};
+    let mut harness = EditorTestHarness::new(80, 24).unwrap();
+    let _fixture = harness
+        .load_buffer_from_text("alpha\nbravo\ncharlie\n")
+        .unwrap();
+    harness.render().unwrap();
+
+    let (x, y) = screen_position_of(&harness, "avo");
+    harness
+        .mouse_click_with_modifiers(x, y, KeyModifiers::CONTROL)
+        .unwrap();
+    assert_eq!(cursor_positions(&harness), vec![0, 8]);
+    // The new cursor is the primary one, as with Add Cursor Below
+    assert_eq!(
+        harness.editor().active_state().cursors.primary().position,
+        8
+    );
+
+    harness.type_text("X").unwrap();
+    harness.assert_buffer_content("Xalpha\nbrXavo\ncharlie\n");
+
+    harness
+        .send_key(KeyCode::Char('z'), KeyModifiers::CONTROL)
+        .unwrap();
+    harness.assert_buffer_content("alpha\nbravo\ncharlie\n");
+    assert_eq!(cursor_positions(&harness), vec![0, 8]);
+
+    // Undo again takes back the added cursor
+    harness
+        .send_key(KeyCode::Char('z'), KeyModifiers::CONTROL)
+        .unwrap();
+    assert_eq!(cursor_positions(&harness), vec![0]);
+}
+
+/// Ctrl+Click on a cursor removes it, except the last one
+#[test]
+fn test_ctrl_click_on_cursor_removes_it() {
+    use crossterm::event::KeyModifiers;
+    let mut harness = EditorTestHarness::new(80, 24).unwrap();
+    let _fixture = harness
+        .load_buffer_from_text("alpha\nbravo\ncharlie\n")
+        .unwrap();
+    harness.render().unwrap();
+
+    let (x, y) = screen_position_of(&harness, "bravo");
+    harness
+        .mouse_click_with_modifiers(x, y, KeyModifiers::CONTROL)
+        .unwrap();
+    assert_eq!(cursor_positions(&harness), vec![0, 6]);
+
+    harness
+        .mouse_click_with_modifiers(x, y, KeyModifiers::CONTROL)
+        .unwrap();
+    assert_eq!(cursor_positions(&harness), vec![0]);
+
+    // The only cursor left stays
+    let (x, y) = screen_position_of(&harness, "alpha");
+    harness
+        .mouse_click_with_modifiers(x, y, KeyModifiers::CONTROL)
+        .unwrap();
+    assert_eq!(cursor_positions(&harness), vec![0]);
+}
+
+/// Alt+Drag places one cursor per line at the mouse column, counting wide
+/// characters by their width on screen
+#[test]
+fn test_alt_drag_places_column_of_cursors() {
+    use crossterm::event::KeyModifiers;
+    let mut harness = EditorTestHarness::new(80, 24).unwrap();
+    let _fixture = harness
+        .load_buffer_from_text("abcdef\n日本語x\nabcdef\nlast\n")
+        .unwrap();
+    harness.render().unwrap();
+
+    let (x, y) = screen_position_of(&harness, "abcdef");
+    harness
+        .mouse_drag_with_modifiers(x + 4, y, x + 4, y + 2, KeyModifiers::ALT)
+        .unwrap();
+    assert_eq!(cursor_positions(&harness), vec![4, 13, 22]);
+
+    harness.type_text("|").unwrap();
+    harness.assert_buffer_content("abcd|ef\n日本|語x\nabcd|ef\nlast\n");
+}
+
+/// A new column drag replaces the cursors, and dragging back toward the
+/// start row takes back the cursors placed on the way
+#[test]
+fn test_alt_drag_back_removes_cursors() {
+    use crossterm::event::{KeyCode, KeyModifiers};
+    let mut harness = EditorTestHarness::new(80, 24).unwrap();
+    let _fixture = harness
+        .load_buffer_from_text("one\ntwo\nsix\nten\n")
+        .unwrap();
+    harness.render().unwrap();
+
+    let (x, y) = screen_position_of(&harness, "one");
+    harness
+        .mouse_drag_with_modifiers(x + 1, y, x + 1, y + 3, KeyModifiers::ALT)
+        .unwrap();
+    assert_eq!(cursor_positions(&harness), vec![1, 5, 9, 13]);
+
+    harness
+        .mouse_drag_with_modifiers(x + 2, y + 2, x + 2, y + 1, KeyModifiers::ALT)
+        .unwrap();
+    assert_eq!(cursor_positions(&harness), vec![6, 10]);
+
+    // One undo takes back the column placed by the drag
+    harness
+        .send_key(KeyCode::Char('z'), KeyModifiers::CONTROL)
+        .unwrap();
+    assert_eq!(cursor_positions(&harness), vec![10]);
+}
+
+/// With `multi_cursor_modifier` set to alt, Alt+Click adds cursors and
+/// Ctrl+Drag places the column
+#[test]
+fn test_alt_click_adds_cursor_when_configured() {
+    use crossterm::event::KeyModifiers;
+    let mut config = fresh::config::Config::default();
+    config.mouse.multi_cursor_modifier = fresh::config::MultiCursorModifier::Alt;
+    let mut harness = EditorTestHarness::with_config(80, 24, config).unwrap();
+    let _fixture = harness
+        .load_buffer_from_text("alpha\nbravo\ncharlie\n")
+        .unwrap();
+    harness.render().unwrap();
+
+    let (x, y) = screen_position_of(&harness, "bravo");
+    harness
+        .mouse_click_with_modifiers(x, y, KeyModifiers::ALT)
+        .unwrap();
+    assert_eq!(cursor_positions(&harness), vec![0, 6]);
+
+    let (x, y) = screen_position_of(&harness, "alpha");
+    harness
+        .mouse_drag_with_modifiers(x + 2, y, x + 2, y + 2, KeyModifiers::CONTROL)
+        .unwrap();
+    assert_eq!(cursor_positions(&harness), vec![2, 8, 14]);
+}
@@ -0,0 +1,215 @@
+//! E2E tests for named layouts (Save Layout As / Switch Layout)
+
+use crate::common::harness::EditorTestHarness;
+use crossterm::event::{KeyCode, KeyModifiers};
+use fresh::config::{Config, Keybinding};
+use fresh::config_io::DirectoryContext;
+use fresh::session::SerializedSplitNode;
+use std::collections::HashMap;
+use tempfile::TempDir;
+
+fn run_command(harness: &mut EditorTestHarness, name: &str) {
+    harness
+        .send_key(KeyCode::Char('p'), KeyModifiers::CONTROL)
+        .unwrap();
+    harness.type_text(name).unwrap();
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    harness.render().unwrap();
+}
+
+/// Run a command whose prompt takes `input`
+fn run_command_with_input(harness: &mut EditorTestHarness, name: &str, input: &str) {
+    run_command(harness, name);
+    harness.type_text(input).unwrap();
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    harness.render().unwrap();
+}
+
+/// The split tree as text, without split ids
+fn layout_shape(node: &SerializedSplitNode) -> String {
+    match node {
+        SerializedSplitNode::Leaf { file_path, .. } => file_path
+            .as_ref()
+            .map(|path| path.display().to_string())
+            .unwrap_or_else(|| "<scratch>".to_string()),
+        SerializedSplitNode::Terminal { .. } => "<terminal>".to_string(),
+        SerializedSplitNode::Panel { .. } => "<panel>".to_string(),
+        SerializedSplitNode::Split {
+            direction,
+            first,
+            second,
+            ratio,
+            ..
+        } => format!(
+            "{:?}({:.2}: {}, {})",
+            direction,
+            ratio,
+            layout_shape(first),
+            layout_shape(second)
+        ),
+    }
+}
+
+fn current_shape(harness: &EditorTestHarness) -> String {
+    layout_shape(&harness.editor().capture_session().split_layout)
+}
+
+fn notified(harness: &EditorTestHarness, text: &str) -> bool {
+    harness
+        .editor()
+        .notifications()
+        .history()
+        .any(|notification| notification.message.contains(text))
+}
+
+fn project_harness(temp_dir: &TempDir, files: &[(&str, &str)]) -> EditorTestHarness {
+    project_harness_with_config(temp_dir, files, Config::default())
+}
+
+fn project_harness_with_config(
+    temp_dir: &TempDir,
+    files: &[(&str, &str)],
+    config: Config,
+) -> EditorTestHarness {
+    let dir_context = DirectoryContext::for_testing(&temp_dir.path().join("dirs"));
+    let project_dir = temp_dir.path().join("project");
+    std::fs::create_dir(&project_dir).unwrap();
+    for (name, content) in files {
+        std::fs::write(project_dir.join(name), content).unwrap();
+    }
+    EditorTestHarness::with_shared_dir_context(120, 30, config, project_dir, dir_context).unwrap()
+}
+
+fn open(harness: &mut EditorTestHarness, temp_dir: &TempDir, name: &str) {
+    let path = temp_dir.path().join("project").join(name);
+    harness.open_file(&path).unwrap();
+}
+
+/// Saving a layout, rearranging the splits and switching back restores the
+/// same tree; the unsaved buffer of the replaced layout stays open and comes
+/// back with the previous layout
+#[test]
+fn test_switch_layout_round_trip() {
+    let temp_dir = TempDir::new().unwrap();
+    let mut harness = project_harness(
+        &temp_dir,
+        &[("a.txt", "Alpha"), ("b.txt", "Beta"), ("c.txt", "Gamma")],
+    );
+
+    open(&mut harness, &temp_dir, "a.txt");
+    run_command(&mut harness, "Split Vertical");
+    open(&mut harness, &temp_dir, "b.txt");
+    run_command_with_input(&mut harness, "Save Layout As", "review");
+    let review = current_shape(&harness);
+    assert!(review.starts_with("Vertical("), "{review}");
+    assert!(
+        review.contains("a.txt") && review.contains("b.txt"),
+        "{review}"
+    );
+
+    run_command(&mut harness, "Close Split");
+    open(&mut harness, &temp_dir, "c.txt");
+    harness.type_text("unsaved ").unwrap();
+    assert_eq!(harness.editor().get_split_count(), 1);
+
+    run_command_with_input(&mut harness, "Switch Layout", "review");
+    assert_eq!(current_shape(&harness), review);
+    assert_eq!(harness.editor().get_split_count(), 2);
+    harness.assert_buffer_content("Beta");
+    assert!(notified(&harness, "Switched to layout 'review'"));
+
+    // The first entry is the layout the switch replaced
+    run_command(&mut harness, "Switch Layout");
+    harness.assert_screen_contains("(previous)");
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    harness.render().unwrap();
+    assert_eq!(current_shape(&harness), "c.txt");
+    harness.assert_buffer_content("unsaved Gamma");
+    assert!(harness.editor().active_state().buffer.is_modified());
+}
+
+/// Files of a layout that no longer exist are skipped and reported
+#[test]
+fn test_switch_layout_skips_missing_files() {
+    let temp_dir = TempDir::new().unwrap();
+    let mut harness = project_harness(&temp_dir, &[("main.txt", "Main"), ("gone.txt", "Gone")]);
+
+    open(&mut harness, &temp_dir, "main.txt");
+    run_command(&mut harness, "Split Horizontal");
+    open(&mut harness, &temp_dir, "gone.txt");
+    run_command_with_input(&mut harness, "Save Layout As", "debug");
+
+    run_command(&mut harness, "Close Split");
+    let gone = temp_dir.path().join("project").join("gone.txt");
+    std::fs::remove_file(&gone).unwrap();
+
+    run_command_with_input(&mut harness, "Switch Layout", "debug");
+    assert_eq!(harness.editor().get_split_count(), 2);
+    let shape = current_shape(&harness);
+    assert!(shape.starts_with("Horizontal("), "{shape}");
+    assert!(shape.contains("main.txt"), "{shape}");
+    assert!(notified(&harness, "skipped 1: gone.txt"));
+    assert!(!gone.exists());
+}
+
+/// Alt+N bound to `switch_layout` with an `index` argument
+fn switch_layout_binding(index: u64) -> Keybinding {
+    Keybinding {
+        key: index.to_string(),
+        modifiers: vec!["alt".to_string()],
+        keys: vec![],
+        action: "switch_layout".to_string(),
+        args: HashMap::from([("index".to_string(), serde_json::json!(index))]),
+        when: None,
+    }
+}
+
+/// Layouts can be switched to by position from a binding's arguments, and
+/// deleting one moves the later ones up
+#[test]
+fn test_switch_layout_by_index_and_delete() {
+    let temp_dir = TempDir::new().unwrap();
+    let mut config = Config::default();
+    config.keybindings.push(switch_layout_binding(1));
+    config.keybindings.push(switch_layout_binding(2));
+    let mut harness =
+        project_harness_with_config(&temp_dir, &[("one.txt", "One"), ("two.txt", "Two")], config);
+
+    open(&mut harness, &temp_dir, "one.txt");
+    run_command_with_input(&mut harness, "Save Layout As", "first");
+    open(&mut harness, &temp_dir, "two.txt");
+    run_command_with_input(&mut harness, "Save Layout As", "second");
+
+    harness
+        .send_key(KeyCode::Char('1'), KeyModifiers::ALT)
+        .unwrap();
+    harness.assert_buffer_content("One");
+    harness
+        .send_key(KeyCode::Char('2'), KeyModifiers::ALT)
+        .unwrap();
+    harness.assert_buffer_content("Two");
+
+    run_command_with_input(&mut harness, "Delete Layout", "first");
+    assert_eq!(
+        harness.editor().get_status_message().unwrap(),
+        "Deleted layout 'first'"
+    );
+    harness
+        .send_key(KeyCode::Char('2'), KeyModifiers::ALT)
+        .unwrap();
+    assert_eq!(
+        harness.editor().get_status_message().unwrap(),
+        "No saved layout 2"
+    );
+    open(&mut harness, &temp_dir, "one.txt");
+    harness
+        .send_key(KeyCode::Char('1'), KeyModifiers::ALT)
+        .unwrap();
+    harness.assert_buffer_content("Two");
+}
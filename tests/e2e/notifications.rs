@@ -0,0 +1,153 @@
+use crate::common::harness::{layout, EditorTestHarness};
+use crossterm::event::{KeyCode, KeyModifiers};
+use fresh::view::notifications::{Notification, NotificationAction, NotificationSeverity};
+use std::time::Duration;
+
+/// Row of the first screen line containing `text`
+fn row_of(harness: &EditorTestHarness, text: &str) -> usize {
+    harness
+        .screen_to_string()
+        .lines()
+        .position(|line| line.contains(text))
+        .unwrap_or_else(|| panic!("{:?} not on screen", text))
+}
+
+/// On a narrow screen at most three notifications stack above the status
+/// bar, long messages are cut short, and the rest are counted
+#[test]
+fn test_notifications_stack_at_narrow_width() {
+    let mut harness = EditorTestHarness::new(30, 24).unwrap();
+    let editor = harness.editor_mut();
+    editor.notify(NotificationSeverity::Info, "Build started");
+    editor.notify(NotificationSeverity::Warning, "Tests flaky");
+    editor.notify(
+        NotificationSeverity::Error,
+        "Indexing failed for every open buffer in the workspace because the disk \
+         ran out of space while writing the word index to its cache",
+    );
+    editor.notify(NotificationSeverity::Info, "Queued fourth");
+    editor.notify(NotificationSeverity::Info, "Queued fifth");
+    harness.render().unwrap();
+
+    harness.assert_screen_contains("+2 more");
+    harness.assert_screen_contains("…");
+    harness.assert_screen_not_contains("Queued fourth");
+    harness.assert_screen_not_contains("cache");
+
+    // Oldest at the top, all of it above the status bar and within the screen
+    let more = row_of(&harness, "+2 more");
+    let first = row_of(&harness, "Build started");
+    let second = row_of(&harness, "Tests flaky");
+    let third = row_of(&harness, "Indexing failed");
+    assert!(more < first && first < second && second < third);
+    let status_row = layout::status_bar_row(24);
+    assert!(row_of(&harness, "Error") < status_row);
+    for line in harness.screen_to_string().lines() {
+        assert!(line.chars().count() <= 30, "{:?}", line);
+    }
+
+    // Too narrow to show anything: the notifications wait in the queue
+    let mut harness = EditorTestHarness::new(10, 24).unwrap();
+    harness
+        .editor_mut()
+        .notify(NotificationSeverity::Info, "Build started");
+    harness.render().unwrap();
+    harness.assert_screen_not_contains("Build");
+}
+
+/// A notification goes away once its timeout has passed on screen, and
+/// the queued one takes its place
+#[test]
+fn test_notification_timeout_lifecycle() {
+    let mut harness = EditorTestHarness::new(80, 24).unwrap();
+    let editor = harness.editor_mut();
+    for i in 1..=4 {
+        editor.notify(NotificationSeverity::Info, format!("Message {}", i));
+    }
+    editor.post_notification(
+        Notification::new(
+            "sticky".to_string(),
+            NotificationSeverity::Warning,
+            "Stays until dismissed".to_string(),
+        )
+        .with_timeout(None),
+    );
+    harness.render().unwrap();
+    harness.assert_screen_contains("Message 1");
+    harness.assert_screen_not_contains("Message 4");
+
+    harness.advance_time(Duration::from_secs(4));
+    assert!(!harness.editor_mut().check_notification_timer());
+
+    // The first three expire; the fourth and the sticky one only now appear
+    harness.advance_time(Duration::from_secs(1));
+    assert!(harness.editor_mut().check_notification_timer());
+    harness.render().unwrap();
+    harness.assert_screen_not_contains("Message 1");
+    harness.assert_screen_contains("Message 4");
+    harness.assert_screen_contains("Stays until dismissed");
+
+    // The fourth gets its full timeout from when it appeared
+    harness.advance_time(Duration::from_secs(4));
+    assert!(!harness.editor_mut().check_notification_timer());
+    harness.advance_time(Duration::from_secs(1));
+    assert!(harness.editor_mut().check_notification_timer());
+    harness.render().unwrap();
+    harness.assert_screen_not_contains("Message 4");
+
+    harness.advance_time(Duration::from_secs(60));
+    harness.editor_mut().check_notification_timer();
+    harness.render().unwrap();
+    harness.assert_screen_contains("Stays until dismissed");
+}
+
+/// Buttons are reachable from the keyboard, and the history lists
+/// notifications that are gone
+#[test]
+fn test_notification_buttons_and_history() {
+    let mut harness = EditorTestHarness::new(80, 24).unwrap();
+    harness.editor_mut().post_notification(
+        Notification::new(
+            "reload".to_string(),
+            NotificationSeverity::Info,
+            "File changed on disk".to_string(),
+        )
+        .with_actions(vec![
+            NotificationAction {
+                id: "reload".to_string(),
+                label: "Reload".to_string(),
+            },
+            NotificationAction {
+                id: "ignore".to_string(),
+                label: "Ignore".to_string(),
+            },
+        ]),
+    );
+    harness.render().unwrap();
+    harness.assert_screen_contains("[Reload]");
+    harness.assert_screen_contains("[Ignore]");
+
+    harness
+        .send_key(
+            KeyCode::Char('n'),
+            KeyModifiers::CONTROL | KeyModifiers::ALT,
+        )
+        .unwrap();
+    assert!(harness.editor().notifications().is_focused());
+    harness.send_key(KeyCode::Tab, KeyModifiers::NONE).unwrap();
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    harness.render().unwrap();
+    assert!(harness.editor().notifications().is_empty());
+    harness.assert_screen_not_contains("File changed on disk");
+
+    // Keys reach the buffer again
+    harness.type_text("x").unwrap();
+    harness.assert_buffer_content("x");
+
+    harness.editor_mut().show_notification_history();
+    harness.render().unwrap();
+    harness.assert_screen_contains("Recent notifications");
+    harness.assert_screen_contains("[Info] File changed on disk");
+}
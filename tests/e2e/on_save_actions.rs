@@ -7,10 +7,14 @@
 //! - $FILE placeholder substitution
 //! - Timeout handling
 //! - Action failure handling
+//! - Formatter failures, file-mode formatters and Format Buffer
 
 use crate::common::harness::EditorTestHarness;
 use crossterm::event::{KeyCode, KeyModifiers};
-use fresh::config::{Config, FormatterConfig, LanguageConfig, OnSaveAction};
+use fresh::config::{
+    Config, FormatFailure, FormatterConfig, LanguageConfig, OnSaveAction, SaveActionFailure,
+};
+use std::path::{Path, PathBuf};
 use tempfile::TempDir;
 
 /// Test format_on_save with formatter (replaces buffer content)
@@ -30,6 +34,7 @@ fn test_format_on_save() {
         args: vec![],
         stdin: true,
         timeout_ms: 5000,
+        on_failure: FormatFailure::Save,
     };
 
     // Create config for "plaintext" language (matches .txt files)
@@ -50,6 +55,14 @@ fn test_format_on_save() {
             formatter: Some(formatter),
             format_on_save: true,
             on_save: vec![],
+            indent_rules: None,
+            on_type_formatting: false,
+            rulers: None,
+            auto_complete: true,
+            completion_trigger_characters: vec![],
+            save_actions: vec![],
+            save_actions_on_failure: SaveActionFailure::Continue,
+            save_actions_timeout_ms: 5000,
         },
     );
 
@@ -66,7 +79,7 @@ fn test_format_on_save() {
     harness
         .send_key(KeyCode::Char('s'), KeyModifiers::CONTROL)
         .unwrap();
-    harness.render().unwrap();
+    wait_for_format(&mut harness);
 
     // Buffer content should be sorted by the formatter
     harness.assert_buffer_content("apple\nbanana\ncherry\n");
@@ -113,6 +126,14 @@ fn test_on_save_linter_style() {
             formatter: None,
             format_on_save: false,
             on_save: vec![action],
+            indent_rules: None,
+            on_type_formatting: false,
+            rulers: None,
+            auto_complete: true,
+            completion_trigger_characters: vec![],
+            save_actions: vec![],
+            save_actions_on_failure: SaveActionFailure::Continue,
+            save_actions_timeout_ms: 5000,
         },
     );
 
@@ -176,6 +197,14 @@ fn test_on_save_action_failure() {
             formatter: None,
             format_on_save: false,
             on_save: vec![action],
+            indent_rules: None,
+            on_type_formatting: false,
+            rulers: None,
+            auto_complete: true,
+            completion_trigger_characters: vec![],
+            save_actions: vec![],
+            save_actions_on_failure: SaveActionFailure::Continue,
+            save_actions_timeout_ms: 5000,
         },
     );
 
@@ -248,6 +277,14 @@ fn test_on_save_file_placeholder() {
             formatter: None,
             format_on_save: false,
             on_save: vec![action],
+            indent_rules: None,
+            on_type_formatting: false,
+            rulers: None,
+            auto_complete: true,
+            completion_trigger_characters: vec![],
+            save_actions: vec![],
+            save_actions_on_failure: SaveActionFailure::Continue,
+            save_actions_timeout_ms: 5000,
         },
     );
 
@@ -295,6 +332,7 @@ fn test_formatter_stdin_mode() {
         args: vec!["a-z".to_string(), "A-Z".to_string()],
         stdin: true,
         timeout_ms: 5000,
+        on_failure: FormatFailure::Save,
     };
 
     let mut config = Config::default();
@@ -314,6 +352,14 @@ fn test_formatter_stdin_mode() {
             formatter: Some(formatter),
             format_on_save: true,
             on_save: vec![],
+            indent_rules: None,
+            on_type_formatting: false,
+            rulers: None,
+            auto_complete: true,
+            completion_trigger_characters: vec![],
+            save_actions: vec![],
+            save_actions_on_failure: SaveActionFailure::Continue,
+            save_actions_timeout_ms: 5000,
         },
     );
 
@@ -330,7 +376,7 @@ fn test_formatter_stdin_mode() {
     harness
         .send_key(KeyCode::Char('s'), KeyModifiers::CONTROL)
         .unwrap();
-    harness.render().unwrap();
+    wait_for_format(&mut harness);
 
     // Buffer content should be uppercase
     harness.assert_buffer_content("HELLO WORLD\n");
@@ -387,6 +433,14 @@ fn test_on_save_stops_on_failure() {
             formatter: None,
             format_on_save: false,
             on_save: vec![action1, action2],
+            indent_rules: None,
+            on_type_formatting: false,
+            rulers: None,
+            auto_complete: true,
+            completion_trigger_characters: vec![],
+            save_actions: vec![],
+            save_actions_on_failure: SaveActionFailure::Continue,
+            save_actions_timeout_ms: 5000,
         },
     );
 
@@ -462,6 +516,7 @@ fn test_formatter_not_found_shows_message() {
         args: vec![],
         stdin: true,
         timeout_ms: 5000,
+        on_failure: FormatFailure::Save,
     };
 
     let mut config = Config::default();
@@ -481,6 +536,14 @@ fn test_formatter_not_found_shows_message() {
             formatter: Some(formatter),
             format_on_save: true,
             on_save: vec![],
+            indent_rules: None,
+            on_type_formatting: false,
+            rulers: None,
+            auto_complete: true,
+            completion_trigger_characters: vec![],
+            save_actions: vec![],
+            save_actions_on_failure: SaveActionFailure::Continue,
+            save_actions_timeout_ms: 5000,
         },
     );
 
@@ -506,3 +569,254 @@ fn test_formatter_not_found_shows_message() {
     // Should show a message about missing formatter
     harness.assert_screen_contains("Formatter");
 }
+
+/// Wait until the active buffer's formatter finished and its save was written
+fn wait_for_format(harness: &mut EditorTestHarness) {
+    let buffer_id = harness.editor().active_buffer();
+    harness
+        .wait_until(|h| {
+            !h.editor().is_buffer_formatting(buffer_id) && !h.editor().is_buffer_saving(buffer_id)
+        })
+        .unwrap();
+}
+
+/// Write an executable formatter script to `dir`
+fn formatter_script(dir: &Path, name: &str, body: &str) -> PathBuf {
+    let path = dir.join(name);
+    std::fs::write(&path, format!("#!/bin/sh\n{}\n", body)).unwrap();
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+    }
+    path
+}
+
+/// A project with `file.txt` holding `content`, whose text files are
+/// formatted on save by `formatter`
+fn formatter_project(
+    temp_dir: &TempDir,
+    content: &str,
+    formatter: FormatterConfig,
+) -> (EditorTestHarness, PathBuf) {
+    let project_dir = temp_dir.path().join("project");
+    std::fs::create_dir(&project_dir).unwrap();
+    let file_path = project_dir.join("file.txt");
+    std::fs::write(&file_path, content).unwrap();
+
+    let mut config = Config::default();
+    config.languages.insert(
+        "plaintext".to_string(),
+        LanguageConfig {
+            extensions: vec!["txt".to_string()],
+            filenames: vec![],
+            grammar: "plaintext".to_string(),
+            comment_prefix: None,
+            auto_indent: false,
+            highlighter: Default::default(),
+            textmate_grammar: None,
+            show_whitespace_tabs: true,
+            use_tabs: false,
+            tab_size: None,
+            formatter: Some(formatter),
+            format_on_save: true,
+            on_save: vec![],
+            indent_rules: None,
+            on_type_formatting: false,
+            rulers: None,
+            auto_complete: true,
+            completion_trigger_characters: vec![],
+            save_actions: vec![],
+            save_actions_on_failure: SaveActionFailure::Continue,
+            save_actions_timeout_ms: 5000,
+        },
+    );
+
+    let mut harness =
+        EditorTestHarness::with_config_and_working_dir(80, 24, config, project_dir).unwrap();
+    harness.open_file(&file_path).unwrap();
+    harness.render().unwrap();
+    (harness, file_path)
+}
+
+fn script_formatter(script: &Path, stdin: bool, on_failure: FormatFailure) -> FormatterConfig {
+    FormatterConfig {
+        command: script.display().to_string(),
+        args: if stdin {
+            vec![]
+        } else {
+            vec!["{file}".to_string()]
+        },
+        stdin,
+        timeout_ms: 5000,
+        on_failure,
+    }
+}
+
+/// A formatter's output replaces only the changed lines, as one undo step,
+/// and the cursor stays where it was
+#[test]
+#[cfg_attr(not(unix), ignore = "On-save actions require Unix-like environment")]
+fn test_format_on_save_keeps_cursor_and_undoes_in_one_step() {
+    let temp_dir = TempDir::new().unwrap();
+    let script = formatter_script(temp_dir.path(), "upper.sh", "tr a-z A-Z");
+    let formatter = script_formatter(&script, true, FormatFailure::Save);
+    let (mut harness, file_path) = formatter_project(&temp_dir, "one\ntwo\nthree\n", formatter);
+
+    // Cursor after "tw"
+    harness.send_key(KeyCode::Down, KeyModifiers::NONE).unwrap();
+    harness
+        .send_key(KeyCode::Right, KeyModifiers::NONE)
+        .unwrap();
+    harness
+        .send_key(KeyCode::Right, KeyModifiers::NONE)
+        .unwrap();
+    assert_eq!(harness.cursor_position(), 6);
+
+    harness
+        .send_key(KeyCode::Char('s'), KeyModifiers::CONTROL)
+        .unwrap();
+    wait_for_format(&mut harness);
+
+    harness.assert_buffer_content("ONE\nTWO\nTHREE\n");
+    assert_eq!(harness.cursor_position(), 6);
+    assert_eq!(
+        std::fs::read_to_string(&file_path).unwrap(),
+        "ONE\nTWO\nTHREE\n"
+    );
+    assert!(!harness.editor().active_state().buffer.is_modified());
+    harness.assert_screen_contains("Saved (formatted with");
+
+    harness
+        .send_key(KeyCode::Char('z'), KeyModifiers::CONTROL)
+        .unwrap();
+    harness.assert_buffer_content("one\ntwo\nthree\n");
+}
+
+/// A failing formatter shows its error and, by default, the unformatted
+/// text is saved anyway
+#[test]
+#[cfg_attr(not(unix), ignore = "On-save actions require Unix-like environment")]
+fn test_failing_formatter_saves_unformatted_text() {
+    let temp_dir = TempDir::new().unwrap();
+    let script = formatter_script(
+        temp_dir.path(),
+        "broken.sh",
+        "echo 'syntax error on line 2' >&2; exit 1",
+    );
+    let formatter = script_formatter(&script, true, FormatFailure::Save);
+    let (mut harness, file_path) = formatter_project(&temp_dir, "text\n", formatter);
+
+    harness.type_text("more ").unwrap();
+    harness
+        .send_key(KeyCode::Char('s'), KeyModifiers::CONTROL)
+        .unwrap();
+    wait_for_format(&mut harness);
+
+    harness.assert_buffer_content("more text\n");
+    assert_eq!(std::fs::read_to_string(&file_path).unwrap(), "more text\n");
+    assert!(!harness.editor().active_state().buffer.is_modified());
+    let status = harness.editor().get_status_message().unwrap().clone();
+    assert!(
+        status.contains("syntax error on line 2") && status.ends_with("saved without formatting"),
+        "{}",
+        status
+    );
+}
+
+/// With `on_failure: "abort"`, a failing formatter leaves the file alone
+#[test]
+#[cfg_attr(not(unix), ignore = "On-save actions require Unix-like environment")]
+fn test_failing_formatter_can_abort_the_save() {
+    let temp_dir = TempDir::new().unwrap();
+    let script = formatter_script(
+        temp_dir.path(),
+        "broken.sh",
+        "echo 'syntax error on line 2' >&2; exit 1",
+    );
+    let formatter = script_formatter(&script, true, FormatFailure::Abort);
+    let (mut harness, file_path) = formatter_project(&temp_dir, "text\n", formatter);
+
+    harness.type_text("more ").unwrap();
+    harness
+        .send_key(KeyCode::Char('s'), KeyModifiers::CONTROL)
+        .unwrap();
+    wait_for_format(&mut harness);
+
+    assert_eq!(std::fs::read_to_string(&file_path).unwrap(), "text\n");
+    assert!(harness.editor().active_state().buffer.is_modified());
+    let status = harness.editor().get_status_message().unwrap().clone();
+    assert!(
+        status.contains("syntax error on line 2") && status.ends_with("not saved"),
+        "{}",
+        status
+    );
+}
+
+/// A formatter that rewrites the file runs after the save, and the buffer
+/// is reloaded from the formatted file
+#[test]
+#[cfg_attr(not(unix), ignore = "On-save actions require Unix-like environment")]
+fn test_file_mode_formatter_reloads_saved_file() {
+    let temp_dir = TempDir::new().unwrap();
+    let script = formatter_script(
+        temp_dir.path(),
+        "upper-in-place.sh",
+        "tr a-z A-Z < \"$1\" > \"$1.tmp\" && mv \"$1.tmp\" \"$1\"",
+    );
+    let formatter = script_formatter(&script, false, FormatFailure::Save);
+    let (mut harness, file_path) = formatter_project(&temp_dir, "one\ntwo\n", formatter);
+
+    harness.send_key(KeyCode::Down, KeyModifiers::NONE).unwrap();
+    harness.send_key(KeyCode::End, KeyModifiers::NONE).unwrap();
+    harness.type_text(" three").unwrap();
+    harness
+        .send_key(KeyCode::Char('s'), KeyModifiers::CONTROL)
+        .unwrap();
+    harness
+        .wait_until(|h| h.get_buffer_content().as_deref() == Some("ONE\nTWO THREE\n"))
+        .unwrap();
+
+    assert_eq!(
+        std::fs::read_to_string(&file_path).unwrap(),
+        "ONE\nTWO THREE\n"
+    );
+    assert!(!harness.editor().active_state().buffer.is_modified());
+    // Still at the end of the edited line
+    assert_eq!(harness.cursor_position(), 13);
+}
+
+/// Format Buffer applies the formatter without saving
+#[test]
+#[cfg_attr(not(unix), ignore = "On-save actions require Unix-like environment")]
+fn test_format_buffer_does_not_save() {
+    let temp_dir = TempDir::new().unwrap();
+    let script = formatter_script(temp_dir.path(), "upper.sh", "tr a-z A-Z");
+    for stdin in [true, false] {
+        let formatter = if stdin {
+            script_formatter(&script, true, FormatFailure::Save)
+        } else {
+            let in_place = formatter_script(
+                temp_dir.path(),
+                "upper-in-place.sh",
+                "tr a-z A-Z < \"$1\" > \"$1.tmp\" && mv \"$1.tmp\" \"$1\"",
+            );
+            script_formatter(&in_place, false, FormatFailure::Save)
+        };
+        let project = TempDir::new().unwrap();
+        let (mut harness, file_path) = formatter_project(&project, "hello\n", formatter);
+
+        harness.editor_mut().format_buffer().unwrap();
+        wait_for_format(&mut harness);
+
+        harness.assert_buffer_content("HELLO\n");
+        assert!(harness.editor().active_state().buffer.is_modified());
+        assert_eq!(std::fs::read_to_string(&file_path).unwrap(), "hello\n");
+        // The copy a file-mode formatter worked on is gone
+        let files: Vec<_> = std::fs::read_dir(file_path.parent().unwrap())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name())
+            .collect();
+        assert_eq!(files, vec!["file.txt"], "stdin: {}", stdin);
+    }
+}
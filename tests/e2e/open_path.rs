@@ -0,0 +1,127 @@
+//! E2E tests for opening paths that aren't plain files
+
+use crate::common::harness::EditorTestHarness;
+use crossterm::event::{KeyCode, KeyModifiers};
+use fresh::config::{Config, MissingFileMode};
+use fresh::services::plugins::api::PluginCommand;
+use std::fs;
+
+/// A directory handed to a plugin's openFile is shown in the file explorer
+/// instead of opening a buffer
+#[test]
+fn test_plugin_open_directory_reveals_it() {
+    let mut harness = EditorTestHarness::with_temp_project(120, 40).unwrap();
+    let project_dir = harness.project_dir().unwrap();
+    fs::create_dir_all(project_dir.join("src/nested")).unwrap();
+    let buffer = harness.editor().active_buffer();
+
+    harness
+        .editor_mut()
+        .handle_plugin_command(PluginCommand::OpenFileAtLocation {
+            path: project_dir.join("src/nested"),
+            line: None,
+            column: None,
+        })
+        .unwrap();
+    harness
+        .wait_until(|h| {
+            h.editor().file_explorer_info().is_some_and(|info| {
+                info.selected_path
+                    .is_some_and(|path| path.ends_with("src/nested"))
+            })
+        })
+        .unwrap();
+
+    let info = harness.editor().file_explorer_info().unwrap();
+    assert!(info.visible);
+    assert!(info.selected_is_dir);
+    assert_eq!(harness.editor().active_buffer(), buffer);
+    assert_eq!(harness.get_buffer_content().unwrap(), "");
+}
+
+/// Opening a directory as a file is an error, not an empty buffer
+#[test]
+fn test_open_file_refuses_directory() {
+    let mut harness = EditorTestHarness::with_temp_project(80, 24).unwrap();
+    let project_dir = harness.project_dir().unwrap();
+    fs::create_dir_all(project_dir.join("docs")).unwrap();
+
+    let err = harness
+        .editor_mut()
+        .open_file(&project_dir.join("docs"))
+        .unwrap_err();
+    assert!(err.to_string().contains("is a directory"), "{err}");
+    assert!(harness.editor().active_state().buffer.file_path().is_none());
+}
+
+/// A FIFO is refused with a message instead of blocking on open
+#[cfg(unix)]
+#[test]
+fn test_open_fifo_is_refused() {
+    let mut harness = EditorTestHarness::with_temp_project(80, 24).unwrap();
+    let fifo = harness.project_dir().unwrap().join("pipe");
+    let made = std::process::Command::new("mkfifo")
+        .arg(&fifo)
+        .status()
+        .unwrap();
+    assert!(made.success());
+
+    assert_eq!(harness.editor_mut().open_path(&fifo).unwrap(), None);
+    let status = harness.editor().get_status_message().cloned().unwrap();
+    assert!(status.contains("fifo"), "status: {status}");
+    assert!(harness.editor_mut().open_file(&fifo).is_err());
+}
+
+/// A symlink to a missing file opens as a new buffer, like a missing file
+#[cfg(unix)]
+#[test]
+fn test_open_dangling_symlink_creates_buffer() {
+    let mut harness = EditorTestHarness::with_temp_project(80, 24).unwrap();
+    let project_dir = harness.project_dir().unwrap();
+    let link = project_dir.join("link.txt");
+    std::os::unix::fs::symlink(project_dir.join("gone.txt"), &link).unwrap();
+
+    let buffer = harness.editor_mut().open_path(&link).unwrap();
+    assert!(buffer.is_some());
+    assert_eq!(
+        harness.editor().active_state().buffer.file_path(),
+        Some(link.as_path())
+    );
+    assert_eq!(harness.get_buffer_content().unwrap(), "");
+}
+
+/// With `files.missing_files: "ask"` a missing file opens only after
+/// confirming, and further missing files are asked about in turn
+#[test]
+fn test_missing_files_ask_before_opening() {
+    let mut config = Config::default();
+    config.files.missing_files = MissingFileMode::Ask;
+    let mut harness = EditorTestHarness::with_temp_project_and_config(120, 24, config).unwrap();
+    let project_dir = harness.project_dir().unwrap();
+    let first = project_dir.join("first.txt");
+    let second = project_dir.join("second.txt");
+
+    assert_eq!(harness.editor_mut().open_path(&first).unwrap(), None);
+    assert_eq!(harness.editor_mut().open_path(&second).unwrap(), None);
+    harness.render().unwrap();
+    harness.assert_screen_contains("first.txt doesn't exist");
+
+    harness.type_text("n").unwrap();
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    assert_eq!(
+        harness.editor().active_state().buffer.file_path(),
+        Some(first.as_path())
+    );
+    harness.render().unwrap();
+    harness.assert_screen_contains("second.txt doesn't exist");
+
+    harness.send_key(KeyCode::Esc, KeyModifiers::NONE).unwrap();
+    assert!(!harness.editor().is_prompting());
+    assert_eq!(
+        harness.editor().active_state().buffer.file_path(),
+        Some(first.as_path())
+    );
+    assert!(!first.exists());
+}
@@ -830,3 +830,173 @@ fn test_paste_crlf_into_prompt() {
     // Prompt should contain the text (newlines may be shown differently in prompt)
     harness.assert_screen_contains("line1");
 }
+
+/// Cycle paste replaces the text just pasted at every cursor with the next
+/// older clipboard history entry, as a single undo step
+#[test]
+fn test_cycle_paste_multi_cursor() {
+    let mut harness = EditorTestHarness::new(80, 24).unwrap();
+
+    harness.type_text("aaa\nbbb\nccc").unwrap();
+    harness
+        .send_key(KeyCode::Home, KeyModifiers::CONTROL)
+        .unwrap();
+    harness.editor_mut().add_cursor_below();
+    harness.editor_mut().add_cursor_below();
+
+    harness
+        .editor_mut()
+        .set_clipboard_for_test("one".to_string());
+    harness
+        .editor_mut()
+        .set_clipboard_for_test("two".to_string());
+    harness.editor_mut().paste_for_test();
+    harness.assert_buffer_content("twoaaa\ntwobbb\ntwoccc");
+
+    harness.editor_mut().cycle_paste();
+    harness.render().unwrap();
+    harness.assert_buffer_content("oneaaa\nonebbb\noneccc");
+
+    // Cycling again wraps around to the newest entry
+    harness.editor_mut().cycle_paste();
+    harness.assert_buffer_content("twoaaa\ntwobbb\ntwoccc");
+
+    // A single undo reverts the last cycle
+    harness
+        .send_key(KeyCode::Char('z'), KeyModifiers::CONTROL)
+        .unwrap();
+    harness.assert_buffer_content("oneaaa\nonebbb\noneccc");
+}
+
+/// Cycle paste does nothing once something else happened after the paste
+#[test]
+fn test_cycle_paste_requires_previous_paste() {
+    let mut harness = EditorTestHarness::new(80, 24).unwrap();
+
+    harness
+        .editor_mut()
+        .set_clipboard_for_test("one".to_string());
+    harness
+        .editor_mut()
+        .set_clipboard_for_test("two".to_string());
+    harness.editor_mut().paste_for_test();
+    harness.type_text("x").unwrap();
+
+    harness.editor_mut().cycle_paste();
+    harness.render().unwrap();
+    harness.assert_buffer_content("twox");
+    assert_eq!(
+        harness.editor().get_status_message().map(String::as_str),
+        Some("Cycle paste only works right after a paste")
+    );
+}
+
+/// The history keeps the configured number of entries, dropping the oldest
+#[test]
+fn test_paste_from_history_picker() {
+    let mut config = fresh::config::Config::default();
+    config.clipboard.history_size = 2;
+    let mut harness = EditorTestHarness::with_config(80, 24, config).unwrap();
+
+    for text in ["first", "second", "third"] {
+        harness
+            .editor_mut()
+            .set_clipboard_for_test(text.to_string());
+    }
+
+    harness
+        .send_key(
+            KeyCode::Char('v'),
+            KeyModifiers::CONTROL | KeyModifiers::SHIFT,
+        )
+        .unwrap();
+    harness.render().unwrap();
+    harness.assert_screen_contains("Paste from history:");
+    harness.assert_screen_contains("third");
+    harness.assert_screen_contains("second");
+    harness.assert_screen_not_contains("first");
+
+    // Newest entry is listed first; pick the one below it
+    harness.send_key(KeyCode::Down, KeyModifiers::NONE).unwrap();
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    harness.render().unwrap();
+    harness.assert_buffer_content("second");
+}
+
+/// Open a Rust file with the cursor at the start of the given line
+fn open_rust_at_line(
+    content: &str,
+    line: usize,
+    config: fresh::config::Config,
+) -> (tempfile::TempDir, EditorTestHarness) {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let file_path = temp_dir.path().join("main.rs");
+    std::fs::write(&file_path, content).unwrap();
+    let mut harness =
+        EditorTestHarness::with_config_and_working_dir(80, 24, config, temp_dir.path().into())
+            .unwrap();
+    harness.open_file(&file_path).unwrap();
+    harness.render().unwrap();
+    for _ in 0..line {
+        harness.send_key(KeyCode::Down, KeyModifiers::NONE).unwrap();
+    }
+    (temp_dir, harness)
+}
+
+/// Test that "Paste and Reindent" fits a deeply indented snippet to a
+/// shallow context, as one undo step
+#[test]
+fn test_paste_and_reindent_deeply_indented_snippet() {
+    let (_temp_dir, mut harness) = open_rust_at_line(
+        "fn main() {\n    let a = 1;\n\n}\n",
+        2,
+        fresh::config::Config::default(),
+    );
+    harness.editor_mut().set_clipboard_for_test(
+        "            if a {\n\n                b();\n            }\n".to_string(),
+    );
+
+    harness
+        .send_key(KeyCode::Char('p'), KeyModifiers::CONTROL)
+        .unwrap();
+    harness.type_text("Paste and Reindent").unwrap();
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    harness.render().unwrap();
+
+    harness.assert_buffer_content(
+        "fn main() {\n    let a = 1;\n    if a {\n\n        b();\n    }\n\n}\n",
+    );
+
+    harness
+        .send_key(KeyCode::Char('z'), KeyModifiers::CONTROL)
+        .unwrap();
+    harness.assert_buffer_content("fn main() {\n    let a = 1;\n\n}\n");
+}
+
+/// Test that with `clipboard.reindent_pastes` plain paste reindents, except
+/// inside a string
+#[test]
+fn test_reindent_pastes_skips_strings() {
+    let mut config = fresh::config::Config::default();
+    config.clipboard.reindent_pastes = true;
+    let (_temp_dir, mut harness) =
+        open_rust_at_line("fn main() {\n    let s = \"\";\n\n}\n", 2, config);
+
+    harness
+        .editor_mut()
+        .paste_text("        x();\n".to_string());
+    harness.assert_buffer_content("fn main() {\n    let s = \"\";\n    x();\n\n}\n");
+
+    // Inside the quotes
+    harness.send_key(KeyCode::Up, KeyModifiers::NONE).unwrap();
+    harness.send_key(KeyCode::Up, KeyModifiers::NONE).unwrap();
+    harness.send_key(KeyCode::End, KeyModifiers::NONE).unwrap();
+    harness.send_key(KeyCode::Left, KeyModifiers::NONE).unwrap();
+    harness.send_key(KeyCode::Left, KeyModifiers::NONE).unwrap();
+    harness.editor_mut().paste_text("a\n        b".to_string());
+    harness.assert_buffer_content("fn main() {\n    let s = \"a\n        b\";\n    x();\n\n}\n");
+}
@@ -0,0 +1,161 @@
+use crate::common::harness::EditorTestHarness;
+use crossterm::event::{KeyCode, KeyModifiers};
+use fresh::model::pending_edits::PendingEdit;
+use std::path::Path;
+use tempfile::TempDir;
+
+/// Build an edit for every occurrence of `old` in `content`
+fn replace_edits(path: &Path, content: &str, old: &str, new: &str) -> Vec<PendingEdit> {
+    content
+        .match_indices(old)
+        .map(|(start, _)| PendingEdit {
+            file: path.to_path_buf(),
+            range: start..start + old.len(),
+            old_text: old.to_string(),
+            new_text: new.to_string(),
+            enabled: true,
+        })
+        .collect()
+}
+
+/// Two files: a.txt with two matches of "foo", b.txt with one
+fn setup() -> (
+    TempDir,
+    std::path::PathBuf,
+    std::path::PathBuf,
+    Vec<PendingEdit>,
+) {
+    let temp_dir = TempDir::new().unwrap();
+    let a = temp_dir.path().join("a.txt");
+    let b = temp_dir.path().join("b.txt");
+    std::fs::write(&a, "foo one\nfoo two\n").unwrap();
+    std::fs::write(&b, "foo three\n").unwrap();
+
+    let mut edits = replace_edits(&a, "foo one\nfoo two\n", "foo", "bar");
+    edits.extend(replace_edits(&b, "foo three\n", "foo", "bar"));
+    (temp_dir, a, b, edits)
+}
+
+/// The header counts follow toggles of single edits and whole files
+#[test]
+fn test_pending_edits_review_counts() {
+    let (_temp_dir, a, _b, edits) = setup();
+    let mut harness = EditorTestHarness::new(100, 30).unwrap();
+    harness.open_file(&a).unwrap();
+
+    harness
+        .editor_mut()
+        .review_pending_edits("Replace foo".to_string(), edits);
+    harness.render().unwrap();
+    harness.assert_screen_contains("Pending edits: Replace foo");
+    harness.assert_screen_contains("3/3 edits in 2/2 files");
+
+    // Cursor starts on the first edit: uncheck it
+    harness
+        .send_key(KeyCode::Char(' '), KeyModifiers::NONE)
+        .unwrap();
+    harness.assert_screen_contains("2/3 edits in 2/2 files");
+
+    // A partially checked file is excluded entirely
+    harness
+        .send_key(KeyCode::Char('f'), KeyModifiers::NONE)
+        .unwrap();
+    harness.assert_screen_contains("1/3 edits in 1/2 files");
+}
+
+/// Expanding an edit shows the line before and after the replacement
+#[test]
+fn test_pending_edits_review_expand_context() {
+    let (_temp_dir, a, _b, edits) = setup();
+    let mut harness = EditorTestHarness::new(100, 30).unwrap();
+    harness.open_file(&a).unwrap();
+
+    harness
+        .editor_mut()
+        .review_pending_edits("Replace foo".to_string(), edits);
+    harness.render().unwrap();
+    harness.assert_screen_not_contains("- foo one");
+
+    harness.send_key(KeyCode::Tab, KeyModifiers::NONE).unwrap();
+    harness.assert_screen_contains("- foo one");
+    harness.assert_screen_contains("+ bar one");
+
+    harness.send_key(KeyCode::Tab, KeyModifiers::NONE).unwrap();
+    harness.assert_screen_not_contains("- foo one");
+}
+
+/// Applying only changes checked edits, opens unopened files, and undoes per buffer
+#[test]
+fn test_pending_edits_review_apply_checked() {
+    let (_temp_dir, a, b, edits) = setup();
+    let mut harness = EditorTestHarness::new(100, 30).unwrap();
+    harness.open_file(&a).unwrap();
+
+    harness
+        .editor_mut()
+        .review_pending_edits("Replace foo".to_string(), edits);
+    harness.render().unwrap();
+
+    // Uncheck the first edit in a.txt, then apply
+    harness
+        .send_key(KeyCode::Char(' '), KeyModifiers::NONE)
+        .unwrap();
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    harness.assert_screen_contains("Applied 2 edits in 2 files");
+    harness.assert_screen_not_contains("Pending edits:");
+
+    harness.open_file(&b).unwrap();
+    harness.assert_buffer_content("bar three\n");
+
+    harness.open_file(&a).unwrap();
+    harness.assert_buffer_content("foo one\nbar two\n");
+
+    // All of a.txt's edits were applied as a single undo step
+    harness
+        .send_key(KeyCode::Char('z'), KeyModifiers::CONTROL)
+        .unwrap();
+    harness.assert_buffer_content("foo one\nfoo two\n");
+}
+
+/// Cancelling leaves every file untouched
+#[test]
+fn test_pending_edits_review_cancel() {
+    let (_temp_dir, a, _b, edits) = setup();
+    let mut harness = EditorTestHarness::new(100, 30).unwrap();
+    harness.open_file(&a).unwrap();
+
+    harness
+        .editor_mut()
+        .review_pending_edits("Replace foo".to_string(), edits);
+    harness.render().unwrap();
+    harness
+        .send_key(KeyCode::Char('q'), KeyModifiers::NONE)
+        .unwrap();
+
+    harness.assert_screen_not_contains("Pending edits:");
+    harness.open_file(&a).unwrap();
+    harness.assert_buffer_content("foo one\nfoo two\n");
+}
+
+/// Edits whose text changed since they were computed are skipped
+#[test]
+fn test_pending_edits_review_skips_stale_edits() {
+    let (_temp_dir, a, _b, mut edits) = setup();
+    let mut harness = EditorTestHarness::new(100, 30).unwrap();
+    harness.open_file(&a).unwrap();
+
+    edits[0].old_text = "xyz".to_string();
+    harness
+        .editor_mut()
+        .review_pending_edits("Replace foo".to_string(), edits);
+    harness.render().unwrap();
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    harness.assert_screen_contains("Applied 2 edits in 2 files (1");
+
+    harness.open_file(&a).unwrap();
+    harness.assert_buffer_content("foo one\nbar two\n");
+}
@@ -0,0 +1,175 @@
+//! E2E tests for the lifetime of plugin-created buffers: what a refresh
+//! keeps, what closing frees, and closing buffers left hidden and unused
+
+use crate::common::harness::EditorTestHarness;
+use fresh::config::Config;
+use fresh::model::event::BufferId;
+use fresh::primitives::text_property::TextPropertyEntry;
+use fresh::services::plugins::api::PluginCommand;
+use fresh::view::theme::DecorationColor;
+use std::time::Duration;
+
+fn create_panel(harness: &mut EditorTestHarness, panel_id: &str, text: &str) -> BufferId {
+    harness
+        .editor_mut()
+        .handle_plugin_command(PluginCommand::CreateVirtualBufferInSplit {
+            name: "*Results*".to_string(),
+            mode: "results-list".to_string(),
+            read_only: true,
+            entries: vec![TextPropertyEntry::text(text)],
+            ratio: 0.5,
+            direction: None,
+            panel_id: Some(panel_id.to_string()),
+            show_line_numbers: false,
+            show_cursors: true,
+            editing_disabled: true,
+            parse_ansi: false,
+            line_wrap: None,
+            persistent: false,
+            plugin: None,
+            request_id: None,
+        })
+        .unwrap();
+    harness.editor().active_buffer()
+}
+
+/// A panel whose split the plugin closed without closing its buffer
+fn create_hidden_buffer(harness: &mut EditorTestHarness, panel_id: &str) -> BufferId {
+    let buffer_id = create_panel(harness, panel_id, "hidden\n");
+    let split_id = harness.editor().get_active_split();
+    harness
+        .editor_mut()
+        .handle_plugin_command(PluginCommand::CloseSplit { split_id })
+        .unwrap();
+    buffer_id
+}
+
+fn refresh(harness: &mut EditorTestHarness, buffer_id: BufferId, text: &str) {
+    let editor = harness.editor_mut();
+    editor
+        .handle_plugin_command(PluginCommand::SetVirtualBufferContent {
+            buffer_id,
+            entries: vec![TextPropertyEntry::text(text)],
+        })
+        .unwrap();
+    editor
+        .handle_plugin_command(PluginCommand::AddOverlay {
+            buffer_id,
+            namespace: None,
+            range: 0..4,
+            color: DecorationColor::Rgb(255, 0, 0),
+            bg_color: None,
+            underline: false,
+            bold: true,
+            italic: false,
+            extend_to_line_end: false,
+        })
+        .unwrap();
+}
+
+fn is_open(harness: &EditorTestHarness, buffer_id: BufferId) -> bool {
+    harness.editor().get_buffer_content(buffer_id).is_some()
+}
+
+/// Opening, refreshing and closing a panel many times leaves every
+/// per-buffer store as it was
+#[test]
+fn test_panel_open_refresh_close_does_not_leak() {
+    let mut harness = EditorTestHarness::new(80, 24).unwrap();
+    let _fixture = harness.load_buffer_from_text("fn main() {}\n").unwrap();
+    harness.render().unwrap();
+    let baseline = harness.editor().buffer_store_stats();
+    let splits = harness.editor().get_split_count();
+
+    for i in 0..500 {
+        let buffer_id = create_panel(&mut harness, "results", "result 0\n");
+        let split_id = harness.editor().get_active_split();
+        refresh(&mut harness, buffer_id, &format!("result {}\n", i));
+        refresh(&mut harness, buffer_id, &format!("result {}\n", i + 1));
+        let editor = harness.editor_mut();
+        editor
+            .handle_plugin_command(PluginCommand::CloseSplit { split_id })
+            .unwrap();
+        editor
+            .handle_plugin_command(PluginCommand::CloseBuffer { buffer_id })
+            .unwrap();
+    }
+
+    harness.render().unwrap();
+    assert_eq!(harness.editor().get_split_count(), splits);
+    assert_eq!(harness.editor().buffer_store_stats(), baseline);
+}
+
+/// New content replaces the overlays of the old content rather than
+/// piling up under them
+#[test]
+fn test_refresh_drops_old_overlays() {
+    let mut harness = EditorTestHarness::new(80, 24).unwrap();
+    let _fixture = harness.load_buffer_from_text("fn main() {}\n").unwrap();
+    let buffer_id = create_panel(&mut harness, "results", "first\n");
+    let decorations = harness.editor().buffer_store_stats().decorations;
+
+    for i in 0..50 {
+        refresh(&mut harness, buffer_id, &format!("line {}\n", i));
+    }
+    assert_eq!(
+        harness.editor().buffer_store_stats().decorations,
+        decorations + 1
+    );
+    harness.render().unwrap();
+    harness.assert_screen_contains("line 49");
+}
+
+/// A plugin buffer no split shows is closed once it's been unused for
+/// `editor.plugin_buffer_idle_close_secs`, and a plugin naming it in a
+/// command counts as using it
+#[test]
+fn test_idle_hidden_plugin_buffer_is_closed() {
+    let mut harness = EditorTestHarness::new(80, 24).unwrap();
+    let _fixture = harness.load_buffer_from_text("fn main() {}\n").unwrap();
+    let buffer_id = create_hidden_buffer(&mut harness, "results");
+    assert_ne!(harness.editor().active_buffer(), buffer_id);
+
+    harness.advance_time(Duration::from_secs(500));
+    assert!(!harness.editor_mut().close_idle_plugin_buffers());
+    refresh(&mut harness, buffer_id, "again\n");
+
+    harness.advance_time(Duration::from_secs(500));
+    assert!(!harness.editor_mut().close_idle_plugin_buffers());
+    assert!(is_open(&harness, buffer_id));
+
+    harness.advance_time(Duration::from_secs(200));
+    assert!(harness.editor_mut().close_idle_plugin_buffers());
+    assert!(!is_open(&harness, buffer_id));
+}
+
+/// Buffers shown in a split, or kept with `setBufferIdleClose(id, false)`,
+/// stay open; a zero timeout turns the sweep off
+#[test]
+fn test_shown_or_kept_plugin_buffers_stay_open() {
+    let mut harness = EditorTestHarness::new(80, 24).unwrap();
+    let _fixture = harness.load_buffer_from_text("fn main() {}\n").unwrap();
+    let shown = create_panel(&mut harness, "shown", "shown\n");
+    let kept = create_hidden_buffer(&mut harness, "kept");
+    harness
+        .editor_mut()
+        .handle_plugin_command(PluginCommand::SetBufferIdleClose {
+            buffer_id: kept,
+            enabled: false,
+        })
+        .unwrap();
+
+    harness.advance_time(Duration::from_secs(3600));
+    assert!(!harness.editor_mut().close_idle_plugin_buffers());
+    assert!(is_open(&harness, shown));
+    assert!(is_open(&harness, kept));
+
+    let mut config = Config::default();
+    config.editor.plugin_buffer_idle_close_secs = 0;
+    let mut harness = EditorTestHarness::with_config(80, 24, config).unwrap();
+    let _fixture = harness.load_buffer_from_text("fn main() {}\n").unwrap();
+    let hidden = create_hidden_buffer(&mut harness, "results");
+    harness.advance_time(Duration::from_secs(3600));
+    assert!(!harness.editor_mut().close_idle_plugin_buffers());
+    assert!(is_open(&harness, hidden));
+}
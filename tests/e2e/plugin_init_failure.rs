@@ -0,0 +1,113 @@
+use crate::common::harness::EditorTestHarness;
+use crossterm::event::{KeyCode, KeyModifiers};
+use fresh::services::plugins::manager::inject_init_error;
+
+/// A plugin runtime that fails to start leaves a working editor and a warning
+#[test]
+fn test_plugin_init_failure_degrades_to_no_plugins() {
+    inject_init_error("V8 platform initialization failed: no /proc");
+    let mut harness = EditorTestHarness::with_temp_project(100, 24).unwrap();
+    assert!(harness.editor().plugin_runtime_failed());
+    assert!(!harness.editor().plugin_manager().is_active());
+
+    harness.render().unwrap();
+    harness.assert_screen_contains("Plugins unavailable");
+    let notification = harness
+        .editor()
+        .notifications()
+        .visible()
+        .next()
+        .unwrap()
+        .message
+        .clone();
+    assert!(notification.contains("no /proc"), "{}", notification);
+    assert!(notification.contains("--no-plugins"), "{}", notification);
+
+    // Files still open and edit normally
+    let file = harness.project_dir().unwrap().join("notes.txt");
+    std::fs::write(&file, "hello\n").unwrap();
+    harness.open_file(&file).unwrap();
+    harness.type_text("well, ").unwrap();
+    harness
+        .send_key(KeyCode::Char('s'), KeyModifiers::CONTROL)
+        .unwrap();
+    assert_eq!(harness.get_buffer_content().unwrap(), "well, hello\n");
+    assert_eq!(std::fs::read_to_string(&file).unwrap(), "well, hello\n");
+}
+
+/// The retry command is offered only after a failure and clears it
+#[test]
+fn test_plugin_init_retry_command() {
+    inject_init_error("temporary directory is full");
+    let mut harness = EditorTestHarness::with_temp_project(100, 24).unwrap();
+    assert!(harness.editor().plugin_runtime_failed());
+
+    harness
+        .send_key(KeyCode::Char('p'), KeyModifiers::CONTROL)
+        .unwrap();
+    harness.type_text("Retry Plugin Init").unwrap();
+    harness.assert_screen_contains("Retry Plugin Initialization");
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    harness.render().unwrap();
+
+    assert!(!harness.editor().plugin_runtime_failed());
+    harness.assert_screen_contains("Plugin runtime started");
+
+    // Nothing left to retry
+    harness.editor_mut().retry_plugin_init();
+    harness.render().unwrap();
+    harness.assert_screen_contains("nothing to retry");
+}
+
+/// A plugin with a syntax error is reported by name with its location, and
+/// the other plugins still load
+#[cfg(feature = "plugins")]
+#[test]
+fn test_plugin_syntax_error_is_reported_and_others_load() {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let project_dir = temp_dir.path().join("project");
+    let plugins_dir = project_dir.join("plugins");
+    std::fs::create_dir_all(&plugins_dir).unwrap();
+    std::fs::write(
+        plugins_dir.join("broken.ts"),
+        "const editor = getEditor();\n\nconst value: number = (1 + ;\n",
+    )
+    .unwrap();
+    std::fs::write(
+        plugins_dir.join("good.ts"),
+        "const editor = getEditor();\neditor.setStatus(\"good plugin loaded\");\n",
+    )
+    .unwrap();
+
+    let dir_context =
+        fresh::config_io::DirectoryContext::for_testing(&temp_dir.path().join("dirs"));
+    let mut harness = EditorTestHarness::with_shared_dir_context(
+        100,
+        24,
+        fresh::config::Config::default(),
+        project_dir,
+        dir_context,
+    )
+    .unwrap();
+    harness.render().unwrap();
+
+    let notification = harness
+        .editor()
+        .notifications()
+        .history()
+        .map(|notification| notification.message.clone())
+        .find(|message| message.contains("Plugin 'broken' failed to load"))
+        .expect("broken plugin is reported");
+    assert!(notification.contains("broken.ts:3:"), "{}", notification);
+
+    let loaded: Vec<String> = harness
+        .editor()
+        .plugin_manager()
+        .list_plugins()
+        .into_iter()
+        .map(|plugin| plugin.name)
+        .collect();
+    assert_eq!(loaded, vec!["good".to_string()]);
+}
@@ -0,0 +1,111 @@
+//! E2E tests for the sanitizing of text plugins put on screen
+
+use crate::common::harness::EditorTestHarness;
+use fresh::primitives::text_property::TextPropertyEntry;
+use fresh::services::plugins::api::PluginCommand;
+use fresh::view::theme::DecorationColor;
+use std::collections::HashMap;
+
+/// Sets the window title, then clears the screen, if it reaches the terminal
+const HOSTILE: &str = "\x1b]0;pwned\x07\x1b[2J";
+
+/// Panic if any cell on screen holds a control character
+fn assert_no_control_cells(harness: &EditorTestHarness) {
+    for (index, cell) in harness.buffer().content().iter().enumerate() {
+        assert!(
+            !cell.symbol().chars().any(char::is_control),
+            "control character {:?} in cell {}:\n{}",
+            cell.symbol(),
+            index,
+            harness.screen_to_string()
+        );
+    }
+}
+
+#[test]
+fn test_status_from_plugin_shows_placeholders() {
+    let mut harness = EditorTestHarness::new(100, 24).unwrap();
+    harness
+        .editor_mut()
+        .handle_plugin_command(PluginCommand::SetStatus {
+            message: format!("{HOSTILE}done\r\nnext\u{9b}"),
+        })
+        .unwrap();
+    harness.render().unwrap();
+
+    assert_eq!(
+        harness.editor().get_status_message().unwrap(),
+        "<1B>]0;pwned<07><1B>[2Jdone next<9B>"
+    );
+    harness.assert_screen_contains("<1B>]0;pwned<07><1B>[2Jdone next<9B>");
+    assert_no_control_cells(&harness);
+}
+
+#[test]
+fn test_overlong_status_from_plugin_is_cut() {
+    let mut harness = EditorTestHarness::new(100, 24).unwrap();
+    harness
+        .editor_mut()
+        .handle_plugin_command(PluginCommand::SetStatus {
+            message: "x".repeat(100_000),
+        })
+        .unwrap();
+
+    let message = harness.editor().get_status_message().unwrap();
+    assert_eq!(message.chars().count(), 4096);
+    assert!(message.ends_with('…'));
+}
+
+#[test]
+fn test_virtual_text_from_plugin_shows_placeholders() {
+    let mut harness = EditorTestHarness::new(100, 24).unwrap();
+    let _fixture = harness.load_buffer_from_text("let value = 1;\n").unwrap();
+    let buffer_id = harness.editor().active_buffer();
+    harness
+        .editor_mut()
+        .handle_plugin_command(PluginCommand::AddVirtualText {
+            buffer_id,
+            virtual_text_id: "hint".to_string(),
+            position: 9,
+            text: format!(": i32{HOSTILE}"),
+            color: DecorationColor::Rgb(128, 128, 128),
+            use_bg: false,
+            before: true,
+        })
+        .unwrap();
+    harness.render().unwrap();
+
+    harness.assert_screen_contains(": i32<1B>]0;pwned<07><1B>[2J");
+    assert_no_control_cells(&harness);
+}
+
+/// Color sequences still color a virtual buffer's text, other escape
+/// sequences show up as text
+#[test]
+fn test_virtual_buffer_entries_from_plugin_show_placeholders() {
+    let mut harness = EditorTestHarness::new(100, 24).unwrap();
+    let entry = |text: &str| TextPropertyEntry {
+        text: text.to_string(),
+        properties: HashMap::new(),
+    };
+    let cmd = PluginCommand::CreateVirtualBufferWithContent {
+        name: "*Output*".to_string(),
+        mode: "output".to_string(),
+        read_only: true,
+        entries: vec![entry(&format!("\x1b[33mwarning\x1b[0m {HOSTILE}end\n"))],
+        show_line_numbers: false,
+        show_cursors: true,
+        editing_disabled: true,
+        parse_ansi: true,
+        request_id: None,
+    };
+    harness.editor_mut().handle_plugin_command(cmd).unwrap();
+    harness.render().unwrap();
+
+    assert_eq!(
+        harness.get_buffer_content().unwrap(),
+        "warning <1B>]0;pwned\x07<1B>[2Jend\n"
+    );
+    harness.assert_screen_contains("warning <1B>]0;pwned<07><1B>[2Jend");
+    assert_no_control_cells(&harness);
+}
@@ -0,0 +1,165 @@
+//! E2E tests for plugins reading buffer text: the size limit of one read,
+//! chunked reads and the bytes charged to each plugin
+
+use crate::common::harness::EditorTestHarness;
+use crossterm::event::{KeyCode, KeyModifiers};
+use fresh::config::Config;
+use std::fs;
+use std::path::PathBuf;
+use tempfile::TempDir;
+
+/// Plugin that reads the active buffer whole, by its first 10 bytes, or in
+/// pieces of 100 bytes, and reports what it got in the status bar
+const TEXT_PLUGIN: &str = r###"
+const editor = getEditor();
+let text = "";
+let pieces = 0;
+
+function checksum(s: string): number {
+    let sum = 0;
+    for (let i = 0; i < s.length; i++) {
+        sum = (sum * 31 + s.charCodeAt(i)) % 1000000007;
+    }
+    return sum;
+}
+
+globalThis.onChunk = function(chunk: { offset: number; text: string; done: boolean }): void {
+    text += chunk.text;
+    pieces += 1;
+    if (chunk.done) {
+        editor.setStatus(`chunks pieces=${pieces} length=${text.length} sum=${checksum(text)}`);
+    }
+};
+
+globalThis.read_all = async function(): Promise<void> {
+    const bufferId = editor.getActiveBufferId();
+    try {
+        const all = await editor.getBufferText(bufferId, 0, editor.getBufferLength(bufferId));
+        editor.setStatus(`read ${all.length}`);
+    } catch (e) {
+        const hint = e.message.includes("readBufferChunks") ? "with hint" : "without hint";
+        editor.setStatus(`refused ${hint}: ${e.message}`);
+    }
+};
+
+globalThis.read_head = async function(): Promise<void> {
+    const head = await editor.getBufferText(editor.getActiveBufferId(), 0, 10);
+    editor.setStatus(`head [${head}]`);
+};
+
+globalThis.read_chunks = function(): void {
+    text = "";
+    pieces = 0;
+    editor.readBufferChunks(editor.getActiveBufferId(), 100, "onChunk");
+};
+
+editor.registerCommand("Text Read All", "Read the whole buffer", "read_all", "normal");
+editor.registerCommand("Text Read Head", "Read the first bytes", "read_head", "normal");
+editor.registerCommand("Text Read Chunks", "Read the buffer in pieces", "read_chunks", "normal");
+editor.setStatus("text plugin ready");
+"###;
+
+/// Lines with characters of 1, 2 and 3 bytes, so pieces of 100 bytes cut
+/// through some of them
+fn content() -> String {
+    (0..200)
+        .map(|i| format!("héllo wörld ✓ line {i}\n"))
+        .collect()
+}
+
+/// The plugin's checksum of `text`
+fn checksum(text: &str) -> u64 {
+    text.encode_utf16()
+        .fold(0, |sum, unit| (sum * 31 + unit as u64) % 1_000_000_007)
+}
+
+/// Editor with the plugin, a 1000-byte read limit and `data.txt` open
+fn setup() -> (TempDir, PathBuf, EditorTestHarness) {
+    let temp_dir = TempDir::new().unwrap();
+    let project_dir = temp_dir.path().join("project");
+    let plugins_dir = project_dir.join("plugins");
+    fs::create_dir_all(&plugins_dir).unwrap();
+    fs::write(plugins_dir.join("text_demo.ts"), TEXT_PLUGIN).unwrap();
+    let file = project_dir.join("data.txt");
+    fs::write(&file, content()).unwrap();
+
+    let mut config = Config::default();
+    config.editor.plugin_text_limit_bytes = 1000;
+    let mut harness =
+        EditorTestHarness::with_config_and_working_dir(200, 24, config, project_dir).unwrap();
+    harness
+        .wait_until(|h| h.get_status_bar().contains("text plugin ready"))
+        .unwrap();
+    harness.open_file(&file).unwrap();
+    (temp_dir, file, harness)
+}
+
+fn run_command(harness: &mut EditorTestHarness, name: &str) {
+    harness
+        .send_key(KeyCode::Char('p'), KeyModifiers::CONTROL)
+        .unwrap();
+    harness.type_text(name).unwrap();
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+}
+
+/// Reading more than the limit at once fails with an error naming the way
+/// out; smaller reads still work
+#[test]
+fn test_get_buffer_text_over_limit_is_refused() {
+    let (_temp_dir, _file, mut harness) = setup();
+
+    run_command(&mut harness, "Text Read All");
+    harness
+        .wait_until(|h| h.get_status_bar().contains("refused"))
+        .unwrap();
+    harness.assert_screen_contains("refused with hint");
+    harness.assert_screen_contains("more than the 1000 bytes");
+
+    run_command(&mut harness, "Text Read Head");
+    harness
+        .wait_until(|h| h.get_status_bar().contains("head [héllo wö]"))
+        .unwrap();
+
+    let transfer = harness.editor().plugin_text_transfer("text_demo").unwrap();
+    assert_eq!(transfer.refused, 1);
+    assert_eq!(transfer.reads, 1);
+    assert_eq!(transfer.total_bytes, 10);
+}
+
+/// A chunked read delivers the whole buffer, larger than the limit, in
+/// pieces that add up to exactly its text, each charged to the plugin
+#[test]
+fn test_read_buffer_chunks_reassembles_content() {
+    let (_temp_dir, _file, mut harness) = setup();
+    let expected = content();
+
+    run_command(&mut harness, "Text Read Chunks");
+    harness
+        .wait_until(|h| h.get_status_bar().contains("chunks pieces="))
+        .unwrap();
+    let status = harness.get_status_bar();
+    assert!(
+        status.contains(&format!(
+            "length={} sum={}",
+            expected.encode_utf16().count(),
+            checksum(&expected)
+        )),
+        "{status}"
+    );
+
+    let pieces: u64 = status
+        .split("pieces=")
+        .nth(1)
+        .and_then(|rest| rest.split_whitespace().next())
+        .and_then(|n| n.parse().ok())
+        .unwrap();
+    assert!(pieces >= expected.len() as u64 / 100, "{status}");
+
+    let transfer = harness.editor().plugin_text_transfer("text_demo").unwrap();
+    assert_eq!(transfer.reads, pieces);
+    assert_eq!(transfer.total_bytes, expected.len() as u64);
+    assert_eq!(transfer.recent_bytes(), expected.len() as u64);
+    assert_eq!(transfer.refused, 0);
+}
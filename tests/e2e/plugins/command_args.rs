@@ -0,0 +1,175 @@
+//! E2E tests for plugin commands with typed arguments
+
+use crate::common::harness::EditorTestHarness;
+use crossterm::event::{KeyCode, KeyModifiers};
+use fresh::config::{Config, Keybinding};
+use std::collections::HashMap;
+use std::fs;
+use tempfile::TempDir;
+
+/// Plugin registering "Greet", which takes a name and a number and reports
+/// the values it was called with
+const GREET_PLUGIN: &str = r###"
+const editor = getEditor();
+
+globalThis.greet = function(options: { args: { who: string; times: number } }): void {
+    const args = options.args;
+    editor.setStatus(`greeted ${args.who} x${args.times} (${typeof args.times})`);
+};
+
+editor.registerCommand("Greet", "Greet someone", "greet", "", {
+    args: [
+        { name: "who", type: "string" },
+        { name: "times", type: "number" },
+    ],
+});
+"###;
+
+/// Ctrl+Alt+`key` bound to run "Greet" with `args`
+fn greet_binding(key: &str, args: serde_json::Value) -> Keybinding {
+    Keybinding {
+        key: key.to_string(),
+        modifiers: vec!["ctrl".to_string(), "alt".to_string()],
+        keys: vec![],
+        action: "run_command".to_string(),
+        args: HashMap::from([
+            ("command".to_string(), serde_json::json!("Greet")),
+            ("args".to_string(), args),
+        ]),
+        when: None,
+    }
+}
+
+fn harness_with_greet(config: Config) -> (TempDir, EditorTestHarness) {
+    let temp_dir = TempDir::new().unwrap();
+    let project_root = temp_dir.path().join("project_root");
+    let plugins_dir = project_root.join("plugins");
+    fs::create_dir_all(&plugins_dir).unwrap();
+    fs::write(plugins_dir.join("greet.ts"), GREET_PLUGIN).unwrap();
+
+    let mut harness =
+        EditorTestHarness::with_config_and_working_dir(100, 30, config, project_root).unwrap();
+    harness
+        .wait_until(|h| {
+            let registry = h.editor().command_registry().read().unwrap();
+            registry.find_by_name("Greet").is_some()
+        })
+        .unwrap();
+    harness.render().unwrap();
+    (temp_dir, harness)
+}
+
+fn type_and_confirm(harness: &mut EditorTestHarness, text: &str) {
+    harness.type_text(text).unwrap();
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    harness.render().unwrap();
+}
+
+fn open_palette(harness: &mut EditorTestHarness) {
+    harness
+        .send_key(KeyCode::Char('p'), KeyModifiers::CONTROL)
+        .unwrap();
+}
+
+fn wait_for_status(harness: &mut EditorTestHarness, expected: &str) {
+    harness
+        .wait_until(|h| h.editor().get_status_message().map(String::as_str) == Some(expected))
+        .unwrap();
+}
+
+/// Picked in the palette, the command prompts for each argument in turn,
+/// asks again for a value of the wrong type, and gets the values by name
+#[test]
+fn test_command_args_from_palette_prompts() {
+    let (_temp_dir, mut harness) = harness_with_greet(Config::default());
+
+    open_palette(&mut harness);
+    type_and_confirm(&mut harness, "Greet");
+    harness.assert_screen_contains("Greet – who: ");
+    type_and_confirm(&mut harness, "Ada Lovelace");
+    harness.assert_screen_contains("Greet – times: ");
+
+    type_and_confirm(&mut harness, "twice");
+    assert_eq!(
+        harness.editor().get_status_message().map(String::as_str),
+        Some("Greet: times must be a number, not \"twice\"")
+    );
+    harness.assert_screen_contains("Greet – times: twice");
+
+    for _ in 0.."twice".len() {
+        harness
+            .send_key(KeyCode::Backspace, KeyModifiers::NONE)
+            .unwrap();
+    }
+    type_and_confirm(&mut harness, "2");
+    wait_for_status(&mut harness, "greeted Ada Lovelace x2 (number)");
+    assert!(!harness.editor().is_prompting());
+}
+
+/// A binding's arguments are checked against the declared types, and the
+/// command runs only when they fit
+#[test]
+fn test_command_args_from_binding() {
+    let mut config = Config::default();
+    config.keybindings.push(greet_binding(
+        "g",
+        serde_json::json!({"who": "Grace", "times": 3}),
+    ));
+    config.keybindings.push(greet_binding(
+        "b",
+        serde_json::json!({"who": "Grace", "times": "many"}),
+    ));
+    config
+        .keybindings
+        .push(greet_binding("n", serde_json::json!({"who": "Grace"})));
+    let (_temp_dir, mut harness) = harness_with_greet(config);
+
+    harness
+        .send_key(
+            KeyCode::Char('g'),
+            KeyModifiers::CONTROL | KeyModifiers::ALT,
+        )
+        .unwrap();
+    wait_for_status(&mut harness, "greeted Grace x3 (number)");
+
+    harness
+        .send_key(
+            KeyCode::Char('b'),
+            KeyModifiers::CONTROL | KeyModifiers::ALT,
+        )
+        .unwrap();
+    assert_eq!(
+        harness.editor().get_status_message().map(String::as_str),
+        Some("Greet: times must be a number, not \"many\"")
+    );
+
+    harness
+        .send_key(
+            KeyCode::Char('n'),
+            KeyModifiers::CONTROL | KeyModifiers::ALT,
+        )
+        .unwrap();
+    assert_eq!(
+        harness.editor().get_status_message().map(String::as_str),
+        Some("Greet: times is required")
+    );
+}
+
+/// `:Greet value…` in the palette gives the arguments in order
+#[test]
+fn test_command_args_from_command_line() {
+    let (_temp_dir, mut harness) = harness_with_greet(Config::default());
+
+    open_palette(&mut harness);
+    type_and_confirm(&mut harness, ":Greet \"Alan T\" 1.5");
+    wait_for_status(&mut harness, "greeted Alan T x1.5 (number)");
+
+    open_palette(&mut harness);
+    type_and_confirm(&mut harness, ":Greet Alan 1 2");
+    assert_eq!(
+        harness.editor().get_status_message().map(String::as_str),
+        Some("Greet: too many values, at most 2 expected")
+    );
+}
@@ -0,0 +1,83 @@
+//! E2E tests for plugin commands sent right after a virtual buffer is created
+
+use crate::common::harness::EditorTestHarness;
+use crossterm::event::{KeyCode, KeyModifiers};
+use fresh::config::Config;
+use std::fs;
+
+/// Plugin that creates a panel and decorates it without waiting a tick
+const STRESS_PLUGIN: &str = r###"
+const editor = getEditor();
+
+globalThis.stress_open = async function(): Promise<void> {
+    const entries = [];
+    for (let i = 0; i < 50; i++) {
+        entries.push({ text: `row ${String(i).padStart(2, "0")}\n`, properties: {} });
+    }
+    const bufferId = await editor.createVirtualBufferInSplit({
+        name: "*Stress*",
+        mode: "stress",
+        read_only: true,
+        entries: entries,
+        ratio: 0.5,
+        panel_id: "stress",
+    });
+    for (let i = 0; i < 50; i++) {
+        const start = i * "row 00\n".length;
+        editor.addOverlay(bufferId, "stress", start, start + 3, 255, 0, 0, false);
+    }
+    editor.setStatus("stress done");
+};
+
+editor.registerCommand("Stress: Open", "Open and decorate a panel", "stress_open", "normal");
+"###;
+
+/// Every decoration issued right after createVirtualBufferInSplit resolves
+/// lands on the new buffer
+#[test]
+fn test_decorations_after_virtual_buffer_creation_all_apply() {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let project_root = temp_dir.path().join("project_root");
+    let plugins_dir = project_root.join("plugins");
+    fs::create_dir_all(&plugins_dir).unwrap();
+    fs::write(plugins_dir.join("stress.ts"), STRESS_PLUGIN).unwrap();
+
+    let mut harness =
+        EditorTestHarness::with_config_and_working_dir(80, 24, Config::default(), project_root)
+            .unwrap();
+    harness.render().unwrap();
+
+    harness
+        .send_key(KeyCode::Char('p'), KeyModifiers::CONTROL)
+        .unwrap();
+    harness.type_text("Stress: Open").unwrap();
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+
+    harness
+        .wait_until(|h| {
+            h.screen_to_string().contains("stress done") && stress_overlays(h).len() == 50
+        })
+        .unwrap();
+
+    // Each overlay covers the word "row" of its own line
+    let state = harness.editor().active_state();
+    let text = state.buffer.to_string().unwrap();
+    let mut ranges = stress_overlays(&harness);
+    ranges.sort_by_key(|range| range.start);
+    assert!(ranges.iter().all(|range| &text[range.clone()] == "row"));
+    assert_eq!(ranges.len(), 50);
+}
+
+/// Ranges of the overlays in the "stress" namespace of the active buffer
+fn stress_overlays(harness: &EditorTestHarness) -> Vec<std::ops::Range<usize>> {
+    let state = harness.editor().active_state();
+    state
+        .overlays
+        .all()
+        .iter()
+        .filter(|o| o.namespace.as_ref().map(|n| n.as_str()) == Some("stress"))
+        .map(|o| o.range(&state.marker_list))
+        .collect()
+}
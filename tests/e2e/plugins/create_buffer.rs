@@ -0,0 +1,131 @@
+//! E2E tests for plugins creating ordinary untitled buffers
+
+use crate::common::harness::EditorTestHarness;
+use crossterm::event::{KeyCode, KeyModifiers};
+use fresh::config::Config;
+use fresh::primitives::highlighter::Language;
+use std::fs;
+use tempfile::TempDir;
+
+/// Plugin that creates buffers from a snippet and from a template file and
+/// reports what it got in the status bar
+const TEMPLATE_PLUGIN: &str = r###"
+const editor = getEditor();
+
+globalThis.new_from_snippet = async function(): Promise<void> {
+    let unknown = "";
+    try {
+        await editor.createBuffer({ content: "x", language: "no-such-language" });
+    } catch (e) {
+        unknown = e.message;
+    }
+    const id = await editor.createBuffer({
+        content: "fn main() {\n}\n",
+        language: "rust",
+        name_hint: "Snippet",
+    });
+    editor.setStatus(`created ${id} active=${editor.getActiveBufferId()} unknown=${unknown}`);
+};
+
+globalThis.new_from_template = async function(): Promise<void> {
+    const id = await editor.createBufferFromFile(editor.pathJoin(editor.getCwd(), "template.rs"));
+    editor.setStatus(`template id=${id}`);
+};
+
+editor.registerCommand("Template: Snippet", "New buffer from a snippet", "new_from_snippet", "normal");
+editor.registerCommand("Template: File", "New buffer from a template", "new_from_template", "normal");
+editor.setStatus("template plugin ready");
+"###;
+
+fn run_command(harness: &mut EditorTestHarness, name: &str) {
+    harness
+        .send_key(KeyCode::Char('p'), KeyModifiers::CONTROL)
+        .unwrap();
+    harness.type_text(name).unwrap();
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+}
+
+fn harness_with_plugin(temp_dir: &TempDir) -> EditorTestHarness {
+    let project_dir = temp_dir.path().join("project");
+    let plugins_dir = project_dir.join("plugins");
+    fs::create_dir_all(&plugins_dir).unwrap();
+    fs::write(plugins_dir.join("template.ts"), TEMPLATE_PLUGIN).unwrap();
+    fs::write(project_dir.join("template.rs"), "struct Template;\n").unwrap();
+
+    let mut harness =
+        EditorTestHarness::with_config_and_working_dir(120, 24, Config::default(), project_dir)
+            .unwrap();
+    harness
+        .wait_until(|h| h.get_status_bar().contains("template plugin ready"))
+        .unwrap();
+    harness
+}
+
+/// A created buffer is an unsaved, modified buffer with the language's
+/// highlighting, and saving it asks for the path it's bound to
+#[test]
+fn test_plugin_created_buffer_saves_as_new_file() {
+    let temp_dir = TempDir::new().unwrap();
+    let mut harness = harness_with_plugin(&temp_dir);
+
+    run_command(&mut harness, "Template: Snippet");
+    harness
+        .wait_until(|h| h.get_status_bar().contains("created"))
+        .unwrap();
+    let status = harness.editor().get_status_message().unwrap().clone();
+    let id = harness.editor().active_buffer().0;
+    assert!(
+        status.starts_with(&format!(
+            "created {} active={} unknown=Unknown language",
+            id, id
+        )),
+        "{}",
+        status
+    );
+
+    let state = harness.editor().active_state();
+    assert_eq!(harness.get_buffer_content().unwrap(), "fn main() {\n}\n");
+    assert!(state.buffer.is_modified());
+    assert!(state.buffer.file_path().is_none());
+    assert!(matches!(state.highlighter.language(), Some(Language::Rust)));
+    harness.render().unwrap();
+    harness.assert_screen_contains("Snippet");
+
+    harness
+        .send_key(KeyCode::Char('s'), KeyModifiers::CONTROL)
+        .unwrap();
+    assert!(harness.editor().is_prompting());
+    harness.type_text("main.rs").unwrap();
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+
+    let path = temp_dir.path().join("project").join("main.rs");
+    assert_eq!(fs::read_to_string(&path).unwrap(), "fn main() {\n}\n");
+    let state = harness.editor().active_state();
+    assert!(!state.buffer.is_modified());
+    assert_eq!(
+        state.buffer.file_path().unwrap().file_name().unwrap(),
+        "main.rs"
+    );
+}
+
+/// A buffer made from a template holds its text but isn't bound to it, and
+/// takes its language from the template's name
+#[test]
+fn test_plugin_buffer_from_template_is_unbound() {
+    let temp_dir = TempDir::new().unwrap();
+    let mut harness = harness_with_plugin(&temp_dir);
+
+    run_command(&mut harness, "Template: File");
+    harness
+        .wait_until(|h| h.get_status_bar().contains("template id="))
+        .unwrap();
+
+    let state = harness.editor().active_state();
+    assert_eq!(harness.get_buffer_content().unwrap(), "struct Template;\n");
+    assert!(state.buffer.file_path().is_none());
+    assert!(matches!(state.highlighter.language(), Some(Language::Rust)));
+}
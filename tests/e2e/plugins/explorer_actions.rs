@@ -0,0 +1,118 @@
+//! E2E tests for plugin actions in the file explorer's context menu
+
+use crate::common::harness::EditorTestHarness;
+use crossterm::event::{KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+use std::fs;
+use std::path::PathBuf;
+use tempfile::TempDir;
+
+/// Plugin with one action for files and one for directories
+const ACTIONS_PLUGIN: &str = r###"
+const editor = getEditor();
+
+globalThis.onExplorerAction = function(data: {
+    action_id: string;
+    path: string;
+    is_dir: boolean;
+}): void {
+    editor.setStatus(
+        `action=${data.action_id} name=${editor.pathBasename(data.path)} dir=${data.is_dir}`
+    );
+};
+
+editor.registerExplorerAction({
+    id: "count_lines",
+    label: "Count Lines",
+    when: "file",
+    handler: "onExplorerAction",
+});
+editor.registerExplorerAction({
+    id: "dir_stats",
+    label: "Directory Stats",
+    when: "dir",
+    handler: "onExplorerAction",
+});
+editor.setStatus("explorer actions ready");
+"###;
+
+/// A project with the plugin, src/ and notes.txt
+fn setup() -> (TempDir, PathBuf) {
+    let temp_dir = TempDir::new().unwrap();
+    let project_dir = temp_dir.path().join("project");
+    let plugins_dir = project_dir.join("plugins");
+    fs::create_dir_all(&plugins_dir).unwrap();
+    fs::create_dir_all(project_dir.join("src")).unwrap();
+    fs::write(plugins_dir.join("explorer_actions.ts"), ACTIONS_PLUGIN).unwrap();
+    fs::write(project_dir.join("notes.txt"), "my notes\n").unwrap();
+    (temp_dir, project_dir)
+}
+
+/// Screen column and row of the first occurrence of `text` below the menu bar
+fn position_of(harness: &EditorTestHarness, text: &str) -> (u16, u16) {
+    harness
+        .screen_to_string()
+        .lines()
+        .enumerate()
+        .skip(1)
+        .find_map(|(row, line)| {
+            let byte = line.find(text)?;
+            Some((line[..byte].chars().count() as u16, row as u16))
+        })
+        .unwrap_or_else(|| panic!("'{}' is not on screen", text))
+}
+
+fn right_click(harness: &mut EditorTestHarness, (col, row): (u16, u16)) {
+    harness
+        .send_mouse(MouseEvent {
+            kind: MouseEventKind::Down(MouseButton::Right),
+            column: col,
+            row,
+            modifiers: KeyModifiers::NONE,
+        })
+        .unwrap();
+    harness.render().unwrap();
+}
+
+/// Plugin entries are offered only on the kind of entry they asked for, and
+/// choosing one calls the handler with the entry
+#[test]
+fn test_plugin_explorer_actions() {
+    let (_temp_dir, project_dir) = setup();
+    let mut harness =
+        EditorTestHarness::with_config_and_working_dir(120, 30, Default::default(), project_dir)
+            .unwrap();
+    harness
+        .wait_until(|h| h.get_status_bar().contains("explorer actions ready"))
+        .unwrap();
+    harness.editor_mut().focus_file_explorer();
+    harness.wait_for_file_explorer_item("notes.txt").unwrap();
+
+    let notes = position_of(&harness, "notes.txt");
+    right_click(&mut harness, notes);
+    harness.assert_screen_contains("Copy Path");
+    harness.assert_screen_contains("Count Lines");
+    harness.assert_screen_not_contains("Directory Stats");
+
+    let entry = position_of(&harness, "Count Lines");
+    harness.mouse_click(entry.0, entry.1).unwrap();
+    harness
+        .wait_until(|h| {
+            h.get_status_bar()
+                .contains("action=count_lines name=notes.txt dir=false")
+        })
+        .unwrap();
+
+    let src = position_of(&harness, "src");
+    right_click(&mut harness, src);
+    harness.assert_screen_contains("Directory Stats");
+    harness.assert_screen_not_contains("Count Lines");
+
+    let entry = position_of(&harness, "Directory Stats");
+    harness.mouse_click(entry.0, entry.1).unwrap();
+    harness
+        .wait_until(|h| {
+            h.get_status_bar()
+                .contains("action=dir_stats name=src dir=true")
+        })
+        .unwrap();
+}
@@ -0,0 +1,77 @@
+//! E2E tests for the plugin file explorer API
+
+use crate::common::harness::EditorTestHarness;
+use crossterm::event::{KeyCode, KeyModifiers};
+use fresh::services::plugins::api::PluginCommand;
+use std::fs;
+use std::path::PathBuf;
+use tempfile::TempDir;
+
+/// Plugin that reports each selection change along with what
+/// getFileExplorerState() says at that moment
+const EXPLORER_PLUGIN: &str = r###"
+const editor = getEditor();
+
+globalThis.onExplorerSelection = function(data: {
+    path: string | null;
+    is_dir: boolean;
+}): void {
+    const state = editor.getFileExplorerState();
+    const agree = state !== null
+        && state.selected_path === data.path
+        && state.selected_is_dir === data.is_dir;
+    const name = data.path === null ? "none" : editor.pathBasename(data.path);
+    editor.setStatus(`selected=${name} dir=${data.is_dir} agree=${agree}`);
+};
+
+editor.on("file_explorer_selection_changed", "onExplorerSelection");
+editor.setStatus("explorer plugin ready");
+"###;
+
+fn setup() -> (TempDir, PathBuf) {
+    let temp_dir = TempDir::new().unwrap();
+    let project_dir = temp_dir.path().join("project");
+    let plugins_dir = project_dir.join("plugins");
+    fs::create_dir_all(&plugins_dir).unwrap();
+    fs::create_dir_all(project_dir.join("src/nested")).unwrap();
+    fs::write(project_dir.join("src/nested/deep.rs"), "").unwrap();
+    fs::write(plugins_dir.join("explorer_demo.ts"), EXPLORER_PLUGIN).unwrap();
+    (temp_dir, project_dir)
+}
+
+/// Reveals and keyboard navigation both fire the hook, and the payload
+/// matches the state snapshot
+#[test]
+fn test_file_explorer_selection_hook_matches_state() {
+    let (_temp_dir, project_dir) = setup();
+    let mut harness =
+        EditorTestHarness::with_config_and_working_dir(120, 30, Default::default(), project_dir)
+            .unwrap();
+    harness
+        .wait_until(|h| h.get_status_bar().contains("explorer plugin ready"))
+        .unwrap();
+
+    harness
+        .editor_mut()
+        .handle_plugin_command(PluginCommand::RevealInFileExplorer {
+            path: "src/nested/deep.rs".into(),
+        })
+        .unwrap();
+    harness
+        .wait_until(|h| {
+            h.get_status_bar()
+                .contains("selected=deep.rs dir=false agree=true")
+        })
+        .unwrap();
+
+    // Moving up from deep.rs selects its parent directory
+    harness.editor_mut().focus_file_explorer();
+    harness.wait_for_file_explorer().unwrap();
+    harness.send_key(KeyCode::Up, KeyModifiers::NONE).unwrap();
+    harness
+        .wait_until(|h| {
+            h.get_status_bar()
+                .contains("selected=nested dir=true agree=true")
+        })
+        .unwrap();
+}
@@ -0,0 +1,82 @@
+//! E2E tests for plugin hook handlers that set themselves off
+
+use crate::common::harness::EditorTestHarness;
+use fresh::config::Config;
+use std::fs;
+
+/// Plugin whose handler of text insertions inserts text again, setting
+/// itself off without end
+const LOOP_PLUGIN: &str = r###"
+const editor = getEditor();
+let runs = 0;
+
+globalThis.loop_after_insert = function(args: { buffer_id: number }): boolean {
+    runs++;
+    editor.setStatus(`runs=${runs}`);
+    editor.executeAction("insert_tab");
+    return true;
+};
+
+editor.on("after_insert", "loop_after_insert");
+"###;
+
+fn harness_with_loop_plugin(chain_depth: u32) -> (tempfile::TempDir, EditorTestHarness) {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let project_root = temp_dir.path().join("project_root");
+    let plugins_dir = project_root.join("plugins");
+    fs::create_dir_all(&plugins_dir).unwrap();
+    fs::write(plugins_dir.join("self_insert.ts"), LOOP_PLUGIN).unwrap();
+    let file = project_root.join("doc.txt");
+    fs::write(&file, "").unwrap();
+
+    let mut config = Config::default();
+    config.editor.plugin_hook_chain_depth = chain_depth;
+    let mut harness =
+        EditorTestHarness::with_config_and_working_dir(80, 24, config, project_root).unwrap();
+    harness.open_file(&file).unwrap();
+    harness.render().unwrap();
+    (temp_dir, harness)
+}
+
+/// The loop is cut at the configured depth, once per edit, and the warning
+/// names the plugin and hook
+#[test]
+fn test_self_triggering_handler_is_cut_at_chain_depth() {
+    let (_temp_dir, mut harness) = harness_with_loop_plugin(3);
+
+    harness.type_text("a").unwrap();
+    harness
+        .wait_until(|h| h.editor().plugin_hook_loop_trips("self_insert") == 1)
+        .unwrap();
+    // Run by the typed "a", then by the insertions at depths 1 to 3
+    assert_eq!(
+        harness.editor().get_status_message().map(String::as_str),
+        Some("runs=4")
+    );
+    let notification = harness
+        .editor()
+        .notifications()
+        .visible()
+        .next()
+        .unwrap()
+        .message
+        .clone();
+    assert!(notification.contains("self_insert"), "{}", notification);
+    assert!(notification.contains("after_insert"), "{}", notification);
+
+    // The editor still takes edits, each starting a new chain
+    harness.type_text("b").unwrap();
+    harness
+        .wait_until(|h| h.editor().plugin_hook_loop_trips("self_insert") == 2)
+        .unwrap();
+    assert_eq!(
+        harness.editor().get_status_message().map(String::as_str),
+        Some("runs=8")
+    );
+    let content = harness.get_buffer_content().unwrap();
+    assert!(
+        content.contains('a') && content.contains('b'),
+        "{:?}",
+        content
+    );
+}
@@ -0,0 +1,90 @@
+//! E2E tests for the order of plugin hooks and the commands their handlers send
+
+use crate::common::harness::EditorTestHarness;
+use crossterm::event::{KeyCode, KeyModifiers};
+use fresh::config::Config;
+use std::fs;
+
+/// Plugin that inserts a character on every prompt change and checks that the
+/// next change sees the buffer grown by it
+const ORDER_PLUGIN: &str = r###"
+const editor = getEditor();
+let expected = -1;
+let hooks = 0;
+let stale = 0;
+
+globalThis.order_start = function(): void {
+    editor.startPrompt("Order: ", "order");
+};
+
+globalThis.order_prompt_changed = function(args: { prompt_type: string; input: string }): boolean {
+    if (args.prompt_type !== "order") {
+        return true;
+    }
+    const bufferId = editor.getActiveBufferId();
+    const length = editor.getBufferLength(bufferId);
+    if (expected >= 0 && length !== expected) {
+        stale++;
+    }
+    expected = length + 1;
+    editor.insertText(bufferId, 0, "x");
+    hooks++;
+    editor.setStatus(`hooks=${hooks} stale=${stale}`);
+    return true;
+};
+
+editor.on("prompt_changed", "order_prompt_changed");
+editor.registerCommand("Order: Start", "Start the order prompt", "order_start", "normal");
+"###;
+
+/// A hook's handlers see the effects of the commands sent by the handlers of
+/// the hook before it
+#[test]
+fn test_hook_sees_commands_of_previous_hook() {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let project_root = temp_dir.path().join("project_root");
+    let plugins_dir = project_root.join("plugins");
+    fs::create_dir_all(&plugins_dir).unwrap();
+    fs::write(plugins_dir.join("order.ts"), ORDER_PLUGIN).unwrap();
+    let file = project_root.join("doc.txt");
+    fs::write(&file, "end\n").unwrap();
+
+    let mut harness =
+        EditorTestHarness::with_config_and_working_dir(80, 24, Config::default(), project_root)
+            .unwrap();
+    harness.open_file(&file).unwrap();
+    harness.render().unwrap();
+
+    harness
+        .send_key(KeyCode::Char('p'), KeyModifiers::CONTROL)
+        .unwrap();
+    harness.type_text("Order: Start").unwrap();
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    harness.wait_until(|h| h.editor().is_prompting()).unwrap();
+
+    const ITERATIONS: usize = 50;
+    for i in 1..=ITERATIONS {
+        harness.type_text("a").unwrap();
+        let status = format!("hooks={i} ");
+        harness
+            .wait_until(|h| {
+                h.editor()
+                    .get_status_message()
+                    .is_some_and(|s| s.starts_with(&status))
+            })
+            .unwrap();
+        assert_eq!(
+            harness.editor().get_status_message().unwrap(),
+            &format!("hooks={i} stale=0")
+        );
+    }
+
+    harness
+        .wait_until(|h| {
+            h.get_buffer_content()
+                .is_some_and(|text| text == format!("{}end\n", "x".repeat(ITERATIONS)))
+        })
+        .unwrap();
+}
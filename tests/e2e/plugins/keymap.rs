@@ -0,0 +1,62 @@
+//! E2E tests for the plugin keymap introspection API
+
+use crate::common::harness::EditorTestHarness;
+use fresh::config::{Config, Keybinding};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use tempfile::TempDir;
+
+/// Plugin whose editor mode binds Ctrl+K, then reports what getKeymap says
+/// about that key
+const KEYMAP_PLUGIN: &str = r###"
+const editor = getEditor();
+
+editor.defineMode("quick-nav", null, [["C-k", "quick_nav_jump"]], false);
+editor.setEditorMode("quick-nav");
+
+editor.getKeymap("normal").then((bindings) => {
+    const binding = bindings.find((b) => b.key === "Ctrl+K");
+    const shadowed = binding.shadowed.map((s) => `${s.action}/${s.source}`).join(",");
+    editor.setStatus(
+        `ctrl+k=${binding.action} source=${binding.source} plugin=${binding.plugin} ` +
+        `mode=${binding.mode} shadowed=${shadowed}`
+    );
+});
+"###;
+
+/// A project loading the plugin, and a config rebinding Ctrl+K
+fn setup() -> (TempDir, PathBuf, Config) {
+    let temp_dir = TempDir::new().unwrap();
+    let project_dir = temp_dir.path().join("project");
+    let plugins_dir = project_dir.join("plugins");
+    fs::create_dir_all(&plugins_dir).unwrap();
+    fs::write(plugins_dir.join("keymap_demo.ts"), KEYMAP_PLUGIN).unwrap();
+
+    let mut config = Config::default();
+    config.keybindings.push(Keybinding {
+        key: "k".to_string(),
+        modifiers: vec!["ctrl".to_string()],
+        keys: vec![],
+        action: "command_palette".to_string(),
+        args: HashMap::new(),
+        when: None,
+    });
+    (temp_dir, project_dir, config)
+}
+
+/// getKeymap reports the plugin's mode binding over the user binding and the
+/// default it overrides
+#[test]
+fn test_get_keymap_reports_provenance() {
+    let (_temp_dir, project_dir, config) = setup();
+    let mut harness =
+        EditorTestHarness::with_config_and_working_dir(160, 30, config, project_dir).unwrap();
+    harness
+        .wait_until(|h| h.get_status_bar().contains("ctrl+k="))
+        .unwrap();
+    harness.assert_screen_contains(
+        "ctrl+k=quick_nav_jump source=mode plugin=keymap_demo mode=quick-nav \
+         shadowed=command_palette/user,delete_to_line_end/keymap",
+    );
+}
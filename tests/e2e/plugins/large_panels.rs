@@ -0,0 +1,104 @@
+//! E2E tests for plugin panels with tens of thousands of entries
+
+use crate::common::harness::EditorTestHarness;
+use crossterm::event::{KeyCode, KeyModifiers};
+use fresh::config::Config;
+use std::fs;
+
+const ENTRIES: usize = 10_000;
+
+/// Plugin opening a panel of `ENTRIES` entries, appending one more right
+/// after, and reporting the entry under the cursor
+const LARGE_PANEL_PLUGIN: &str = r###"
+const editor = getEditor();
+let panelId: number | null = null;
+
+globalThis.large_panel_open = async function(): Promise<void> {
+    const entries = [];
+    for (let i = 0; i < 10000; i++) {
+        entries.push({ text: `entry ${i}\n`, properties: { index: i } });
+    }
+    const result = await editor.createVirtualBufferInSplit({
+        name: "*Large*",
+        mode: "large-panel",
+        read_only: true,
+        entries,
+        ratio: 0.5,
+    });
+    panelId = result.buffer_id;
+    editor.appendVirtualBufferContent(panelId, [
+        { text: "appended\n", properties: { index: 10000 } },
+    ]);
+};
+
+globalThis.large_panel_where = function(): void {
+    if (panelId === null) {
+        return;
+    }
+    const props = editor.getTextPropertiesAtCursor(panelId);
+    editor.setStatus(`index=${props.length > 0 ? props[0].index : "none"}`);
+};
+
+editor.registerCommand("Large Panel: Open", "Open the large panel", "large_panel_open", "normal");
+editor.registerCommand("Large Panel: Where", "Report the entry", "large_panel_where", "normal");
+"###;
+
+fn run_command(harness: &mut EditorTestHarness, name: &str) {
+    harness
+        .send_key(KeyCode::Char('p'), KeyModifiers::CONTROL)
+        .unwrap();
+    harness.type_text(name).unwrap();
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+}
+
+/// The panel shows its first entries at once, takes in the rest over the
+/// next ticks with the appended entry last, and finds the properties of an
+/// entry far down
+#[test]
+fn test_large_panel_is_taken_in_by_chunks() {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let project_root = temp_dir.path().join("project_root");
+    let plugins_dir = project_root.join("plugins");
+    fs::create_dir_all(&plugins_dir).unwrap();
+    fs::write(plugins_dir.join("large_panel.ts"), LARGE_PANEL_PLUGIN).unwrap();
+
+    let mut harness =
+        EditorTestHarness::with_config_and_working_dir(100, 30, Config::default(), project_root)
+            .unwrap();
+    harness.render().unwrap();
+
+    run_command(&mut harness, "Large Panel: Open");
+    harness
+        .wait_until(|h| h.screen_to_string().contains("entry 0"))
+        .unwrap();
+    harness
+        .wait_until(|h| {
+            let buffer_id = h.editor().active_buffer();
+            !h.editor().has_pending_virtual_content(buffer_id)
+                && h.get_buffer_content()
+                    .is_some_and(|content| content.ends_with("appended\n"))
+        })
+        .unwrap();
+
+    let content = harness.get_buffer_content().unwrap();
+    let lines: Vec<&str> = content.lines().collect();
+    assert_eq!(lines.len(), ENTRIES + 1);
+    for (i, line) in lines[..ENTRIES].iter().enumerate() {
+        assert_eq!(*line, format!("entry {}", i));
+    }
+
+    harness.editor_mut().goto_line_col(7001, None);
+    harness.render().unwrap();
+    harness.assert_screen_contains("entry 7000");
+    let index = harness.editor().get_text_properties_at_cursor().unwrap()[0]
+        .get("index")
+        .cloned();
+    assert_eq!(index, Some(serde_json::json!(7000)));
+
+    run_command(&mut harness, "Large Panel: Where");
+    harness
+        .wait_until(|h| h.editor().get_status_message().map(String::as_str) == Some("index=7000"))
+        .unwrap();
+}
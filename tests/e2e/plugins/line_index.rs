@@ -0,0 +1,92 @@
+//! E2E tests for plugins converting between byte offsets and lines
+
+use crate::common::harness::EditorTestHarness;
+use crossterm::event::{KeyCode, KeyModifiers};
+use fresh::config::Config;
+use std::fs;
+use tempfile::TempDir;
+
+/// Plugin that converts a few positions of the active buffer and reports
+/// the results in the status bar
+const LINE_INDEX_PLUGIN: &str = r###"
+const editor = getEditor();
+
+globalThis.report_lines = async function(): Promise<void> {
+    const bufferId = editor.getActiveBufferId();
+    const length = editor.getBufferLength(bufferId);
+    const at = async (byte: number) => {
+        const { line, column } = await editor.byteToLineCol(bufferId, byte);
+        return `${line}:${column}`;
+    };
+    let missing = "";
+    try {
+        await editor.lineColToByte(bufferId, 9, 0);
+    } catch (e) {
+        missing = e.message;
+    }
+    editor.setStatus(
+        `lines byte6=${await at(6)} end=${await at(length)} ` +
+        `line0end=${await editor.lineColToByte(bufferId, 0, 99)} ` +
+        `line2col2=${await editor.lineColToByte(bufferId, 2, 2)} ` +
+        `cursor=${editor.getCursorLine()} missing=${missing}`
+    );
+};
+
+editor.registerCommand("Report Lines", "Convert some positions", "report_lines", "normal");
+editor.setStatus("line plugin ready");
+"###;
+
+/// Run the plugin's command and wait for a report containing `expected`
+fn report(harness: &mut EditorTestHarness, expected: &str) -> String {
+    harness
+        .send_key(KeyCode::Char('p'), KeyModifiers::CONTROL)
+        .unwrap();
+    harness.type_text("Report Lines").unwrap();
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    harness
+        .wait_until(|h| h.get_status_bar().contains(expected))
+        .unwrap();
+    harness.editor().get_status_message().unwrap().clone()
+}
+
+/// Conversions go through the buffer's line index and follow edits; a CRLF
+/// line ends at its line feed and the last line has no line feed
+#[test]
+fn test_plugin_line_col_conversions() {
+    let temp_dir = TempDir::new().unwrap();
+    let project_dir = temp_dir.path().join("project");
+    let plugins_dir = project_dir.join("plugins");
+    fs::create_dir_all(&plugins_dir).unwrap();
+    fs::write(plugins_dir.join("line_demo.ts"), LINE_INDEX_PLUGIN).unwrap();
+    let file = project_dir.join("data.txt");
+    fs::write(&file, "one\r\ntwo\nthree").unwrap();
+
+    let mut harness =
+        EditorTestHarness::with_config_and_working_dir(200, 24, Config::default(), project_dir)
+            .unwrap();
+    harness
+        .wait_until(|h| h.get_status_bar().contains("line plugin ready"))
+        .unwrap();
+    harness.open_file(&file).unwrap();
+
+    let status = report(&mut harness, "cursor=1");
+    assert!(
+        status
+            .starts_with("lines byte6=1:1 end=2:5 line0end=4 line2col2=11 cursor=1 missing=Line 9")
+            && status.ends_with("(3 lines)"),
+        "{}",
+        status
+    );
+
+    harness.type_text("new\n").unwrap();
+    let status = report(&mut harness, "cursor=2");
+    assert!(
+        status
+            .starts_with("lines byte6=1:2 end=3:5 line0end=3 line2col2=11 cursor=2 missing=Line 9")
+            && status.ends_with("(4 lines)"),
+        "{}",
+        status
+    );
+}
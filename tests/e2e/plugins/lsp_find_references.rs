@@ -128,7 +128,10 @@ done
             auto_start: true, // Auto-start so LSP starts when file is opened
             process_limits: fresh::services::process_limits::ProcessLimits::default(),
             initialization_options: None,
-        },
+            name: None,
+            primary: false,
+        }
+        .into(),
     );
 
     // Create harness with config and working directory
@@ -2,8 +2,27 @@
 //! These tests are only compiled when the "plugins" feature is enabled.
 
 pub mod audit_mode;
+pub mod buffer_text;
+pub mod command_args;
+pub mod command_queue;
+pub mod create_buffer;
+pub mod explorer_actions;
+pub mod file_explorer_api;
 pub mod git;
 pub mod gutter;
+pub mod hook_loops;
+pub mod hook_order;
+pub mod keymap;
+pub mod large_panels;
+pub mod line_index;
 pub mod lsp_find_references;
+pub mod panel_restore;
+pub mod path_watch;
 pub mod plugin;
+pub mod progress;
+pub mod protected_edits;
+pub mod retained_views;
+pub mod save_actions;
+pub mod split_hooks;
+pub mod startup_profile;
 pub mod theme_editor;
@@ -0,0 +1,128 @@
+//! E2E tests for restoring persistent plugin panels with the session
+
+use crate::common::harness::EditorTestHarness;
+use crossterm::event::{KeyCode, KeyModifiers};
+use fresh::config::Config;
+use fresh::config_io::DirectoryContext;
+use std::fs;
+use tempfile::TempDir;
+
+/// Plugin with a persistent panel that it refills from the panel_restore hook,
+/// echoing the ids it was given
+const PANEL_PLUGIN: &str = r###"
+const editor = getEditor();
+
+globalThis.panel_demo_open = async function(): Promise<void> {
+    await editor.createVirtualBufferInSplit({
+        name: "*Demo*",
+        mode: "panel-demo",
+        read_only: true,
+        entries: [{ text: "Live demo content\n", properties: {} }],
+        ratio: 0.5,
+        panel_id: "demo",
+        persistent: true,
+    });
+};
+
+editor.registerCommand("Panel Demo: Open", "Open the demo panel", "panel_demo_open", "normal");
+
+globalThis.onPanelRestore = function(args: {
+    plugin: string;
+    panel_id: string;
+    buffer_id: number;
+    split_id: number | null;
+}): boolean {
+    if (args.plugin !== "panel_demo") {
+        return true;
+    }
+    editor.setVirtualBufferContent(args.buffer_id, [
+        {
+            text: `restored ${args.panel_id} buffer=${args.buffer_id} split=${args.split_id}\n`,
+            properties: {},
+        },
+    ]);
+    return true;
+};
+
+editor.on("panel_restore", "onPanelRestore");
+"###;
+
+/// A persistent panel comes back in its own split after a restart, and its
+/// plugin receives the ids of the recreated buffer and split
+#[test]
+fn test_persistent_panel_round_trips_through_session() {
+    let temp_dir = TempDir::new().unwrap();
+    let dir_context = DirectoryContext::for_testing(&temp_dir.path().join("dirs"));
+    let project_dir = temp_dir.path().join("project");
+    let plugins_dir = project_dir.join("plugins");
+    fs::create_dir_all(&plugins_dir).unwrap();
+    fs::write(plugins_dir.join("panel_demo.ts"), PANEL_PLUGIN).unwrap();
+
+    let file = project_dir.join("notes.txt");
+    fs::write(&file, "Notes file content").unwrap();
+
+    // First session: open the panel and save
+    {
+        let mut harness = EditorTestHarness::with_shared_dir_context(
+            80,
+            24,
+            Config::default(),
+            project_dir.clone(),
+            dir_context.clone(),
+        )
+        .unwrap();
+        harness.open_file(&file).unwrap();
+
+        harness
+            .send_key(KeyCode::Char('p'), KeyModifiers::CONTROL)
+            .unwrap();
+        harness.type_text("Panel Demo: Open").unwrap();
+        harness
+            .send_key(KeyCode::Enter, KeyModifiers::NONE)
+            .unwrap();
+        harness
+            .wait_until(|h| h.screen_to_string().contains("Live demo content"))
+            .unwrap();
+
+        harness.editor_mut().save_session().unwrap();
+    }
+
+    // Second session: the plugin refills the recreated panel
+    {
+        let mut harness = EditorTestHarness::with_shared_dir_context(
+            80,
+            24,
+            Config::default(),
+            project_dir.clone(),
+            dir_context.clone(),
+        )
+        .unwrap();
+
+        harness.editor_mut().try_restore_session().unwrap();
+        harness
+            .wait_until(|h| h.screen_to_string().contains("restored demo"))
+            .unwrap();
+
+        assert_eq!(harness.editor().get_split_count(), 2);
+        harness.assert_screen_contains("Notes file content");
+
+        // The hook named the buffer and split that actually show the panel
+        let screen = harness.screen_to_string();
+        let matching = harness
+            .editor()
+            .get_split_areas()
+            .iter()
+            .filter(|(split_id, buffer_id, ..)| {
+                screen.contains(&format!(
+                    "restored demo buffer={} split={}",
+                    buffer_id.0, split_id.0
+                ))
+            })
+            .count();
+        assert_eq!(
+            matching, 1,
+            "Hook ids should match the panel split:\n{}",
+            screen
+        );
+    }
+}
@@ -0,0 +1,124 @@
+//! E2E tests for plugins watching files and globs on disk
+
+use crate::common::harness::EditorTestHarness;
+use crossterm::event::{KeyCode, KeyModifiers};
+use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
+use tempfile::TempDir;
+
+/// Plugin that watches data/*.txt and lists every change it is told about
+const WATCH_PLUGIN: &str = r###"
+const editor = getEditor();
+const events: string[] = [];
+
+globalThis.onDataChanged = function(change: {
+    watch_id: number;
+    path: string;
+    kind: string;
+}): void {
+    events.push(`${change.kind}:${editor.pathBasename(change.path)}:${change.watch_id}`);
+    editor.setStatus(`events=${events.length} ${events.join(" ")}`);
+};
+
+const watchId = editor.watchPath("data/*.txt", "onDataChanged");
+editor.setStatus(`watching ${watchId}`);
+
+globalThis.watch_many = function(): void {
+    try {
+        for (let i = 0; i < 100; i++) {
+            editor.watchPath(`data/file${i}.txt`, "onDataChanged");
+        }
+        editor.setStatus("no limit");
+    } catch (e) {
+        editor.setStatus(`limit: ${e.message}`);
+    }
+};
+
+editor.registerCommand("Watch Many", "Watch many paths", "watch_many", "normal");
+"###;
+
+/// A project with the watching plugin and data/a.txt
+fn setup() -> (TempDir, PathBuf) {
+    let temp_dir = TempDir::new().unwrap();
+    let project_dir = temp_dir.path().join("project");
+    let plugins_dir = project_dir.join("plugins");
+    fs::create_dir_all(&plugins_dir).unwrap();
+    fs::create_dir_all(project_dir.join("data")).unwrap();
+    fs::write(plugins_dir.join("watch_demo.ts"), WATCH_PLUGIN).unwrap();
+    fs::write(project_dir.join("data/a.txt"), "").unwrap();
+    (temp_dir, project_dir)
+}
+
+/// Let the watches be polled once
+fn poll_watches(harness: &mut EditorTestHarness) {
+    harness.sleep(Duration::from_millis(600));
+    harness.process_async_and_render().unwrap();
+}
+
+/// Changes matching the glob reach the handler, and three quick writes to a
+/// file are reported as one change
+#[test]
+fn test_watch_path_reports_debounced_changes() {
+    let (_temp_dir, project_dir) = setup();
+    let mut harness = EditorTestHarness::with_config_and_working_dir(
+        160,
+        24,
+        Default::default(),
+        project_dir.clone(),
+    )
+    .unwrap();
+    harness
+        .wait_until(|h| h.get_status_bar().contains("watching 1"))
+        .unwrap();
+
+    let file = project_dir.join("data/a.txt");
+    for content in ["1", "12", "123"] {
+        fs::write(&file, content).unwrap();
+        poll_watches(&mut harness);
+    }
+    harness
+        .wait_until(|h| h.get_status_bar().contains("events=1 modified:a.txt:1"))
+        .unwrap();
+
+    // Files the glob does not match are ignored
+    fs::write(project_dir.join("data/notes.md"), "skip").unwrap();
+    fs::write(project_dir.join("data/b.txt"), "b").unwrap();
+    fs::remove_file(&file).unwrap();
+    harness
+        .wait_until(|h| {
+            h.get_status_bar()
+                .contains("events=3 modified:a.txt:1 removed:a.txt:1 created:b.txt:1")
+        })
+        .unwrap();
+
+    // Nothing more is reported once the files are quiet
+    for _ in 0..3 {
+        poll_watches(&mut harness);
+    }
+    assert!(harness.get_status_bar().contains("events=3"));
+}
+
+/// Watching more paths than a plugin is allowed throws a clear error
+#[test]
+fn test_watch_path_limit_per_plugin() {
+    let (_temp_dir, project_dir) = setup();
+    let mut harness =
+        EditorTestHarness::with_config_and_working_dir(160, 24, Default::default(), project_dir)
+            .unwrap();
+    harness
+        .wait_until(|h| h.get_status_bar().contains("watching 1"))
+        .unwrap();
+
+    harness
+        .send_key(KeyCode::Char('p'), KeyModifiers::CONTROL)
+        .unwrap();
+    harness.type_text("Watch Many").unwrap();
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    harness
+        .wait_until(|h| h.get_status_bar().contains("limit:"))
+        .unwrap();
+    harness.assert_screen_contains("already has 64 path watches");
+}
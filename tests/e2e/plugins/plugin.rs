@@ -670,7 +670,10 @@ fn test_diagnostics_panel_plugin_loads() {
             auto_start: true,
             process_limits: fresh::services::process_limits::ProcessLimits::default(),
             initialization_options: None,
-        },
+            name: None,
+            primary: false,
+        }
+        .into(),
     );
 
     // Create harness with the project directory and LSP config
@@ -1555,7 +1558,10 @@ fn test_clangd_plugin_file_status_notification() -> std::io::Result<()> {
             auto_start: false,
             process_limits: ProcessLimits::default(),
             initialization_options: None,
-        },
+            name: None,
+            primary: false,
+        }
+        .into(),
     );
 
     let mut harness =
@@ -1624,7 +1630,10 @@ fn test_clangd_plugin_switch_source_header() -> std::io::Result<()> {
             auto_start: false,
             process_limits: ProcessLimits::default(),
             initialization_options: None,
-        },
+            name: None,
+            primary: false,
+        }
+        .into(),
     );
 
     let mut harness =
@@ -1857,7 +1866,10 @@ editor.setStatus("Test diagnostics plugin loaded");
             auto_start: false,
             process_limits: ProcessLimits::default(),
             initialization_options: None,
-        },
+            name: None,
+            primary: false,
+        }
+        .into(),
     );
 
     let mut harness =
@@ -1907,9 +1919,10 @@ editor.setStatus("Test diagnostics plugin loaded");
         .canonicalize()
         .unwrap_or_else(|_| test_file.clone());
     let file_uri = format!("file://{}", canonical_path.to_string_lossy());
-    let diags = stored
+    let diags = &stored
         .get(&file_uri)
-        .expect("Should have diagnostics for test file");
+        .expect("Should have diagnostics for test file")
+        .diagnostics;
     assert_eq!(diags.len(), 1, "Expected exactly one diagnostic");
 
     // Verify the diagnostic content matches what fake LSP sends
@@ -1936,3 +1949,201 @@ editor.setStatus("Test diagnostics plugin loaded");
 
     Ok(())
 }
+
+/// Test that executeActionAwait/executeActionsAwait resolve with what the action did
+#[test]
+fn test_execute_action_await_results() {
+    // Create a temporary project directory
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let project_root = temp_dir.path().join("project_root");
+    fs::create_dir(&project_root).unwrap();
+
+    // Create plugins directory
+    let plugins_dir = project_root.join("plugins");
+    fs::create_dir(&plugins_dir).unwrap();
+
+    // Each result is reported as ok/cursor_moved/buffer_changed bits
+    let test_plugin = r#"
+const editor = getEditor();
+
+function bits(r: ActionResult): string {
+    return `${r.ok ? 1 : 0}${r.cursor_moved ? 1 : 0}${r.buffer_changed ? 1 : 0}`;
+}
+
+globalThis.probe_action_results = async function(): Promise<void> {
+    const moved = await editor.executeActionAwait("move_right");
+    const unknown = await editor.executeActionAwait("no_such_action");
+    await editor.executeActionAwait("move_document_end");
+    const atEnd = await editor.executeActionAwait("move_right");
+    const batch = await editor.executeActionsAwait([
+        { action: "delete_backward" },
+        { action: "bogus_action" },
+        { action: "move_left" },
+    ]);
+    editor.setStatus(
+        `moved=${bits(moved)} unknown=${bits(unknown)}:${unknown.error} ` +
+        `end=${bits(atEnd)} batch=${batch.map(bits).join(",")}`
+    );
+};
+
+editor.registerCommand(
+    "Probe Action Results",
+    "Run actions and report their outcome",
+    "probe_action_results",
+    "normal"
+);
+"#;
+
+    let test_plugin_path = plugins_dir.join("test_action_results.ts");
+    fs::write(&test_plugin_path, test_plugin).unwrap();
+
+    let fixture = TestFixture::new("test.txt", "hello").unwrap();
+
+    let mut harness =
+        EditorTestHarness::with_config_and_working_dir(80, 24, Default::default(), project_root)
+            .unwrap();
+    harness.open_file(&fixture.path).unwrap();
+    harness.render().unwrap();
+
+    harness
+        .send_key(KeyCode::Char('p'), KeyModifiers::CONTROL)
+        .unwrap();
+    harness.type_text("Probe Action Results").unwrap();
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+
+    harness
+        .wait_until(|h| {
+            h.editor()
+                .get_status_message()
+                .is_some_and(|s| s.starts_with("moved="))
+        })
+        .unwrap();
+
+    // A motion that moves, an unknown action, a motion stuck at the buffer end,
+    // and a batch that stops at its unknown action
+    assert_eq!(
+        harness.editor().get_status_message().unwrap(),
+        "moved=110 unknown=000:Unknown action: no_such_action end=100 batch=111,000"
+    );
+    assert_eq!(harness.get_buffer_content().unwrap(), "hell");
+}
+
+/// Test that plugins can read prompt state, close their prompts, and accept suggestions
+#[test]
+fn test_plugin_prompt_state_and_control() {
+    // Create a temporary project directory
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let project_root = temp_dir.path().join("project_root");
+    fs::create_dir(&project_root).unwrap();
+
+    // Create plugins directory
+    let plugins_dir = project_root.join("plugins");
+    fs::create_dir(&plugins_dir).unwrap();
+
+    // Typing "ab" reads the prompt state, then a mismatched close is ignored and
+    // the second suggestion is accepted; the second prompt is closed by the plugin
+    let test_plugin = r#"
+const editor = getEditor();
+let observed = "";
+
+globalThis.probe_prompt_start = function(): void {
+    editor.startPrompt("Pick: ", "picker");
+    editor.setPromptSuggestions([{ text: "one" }, { text: "two" }]);
+};
+
+async function inspectAndAccept(): Promise<void> {
+    await editor.delay(50);
+    const s = editor.getPromptState();
+    observed = `${s.active}:${s.prompt_type}:${s.label}:${s.text}:${s.cursor}:${s.selected_suggestion_index}`;
+    editor.closePrompt("other");
+    editor.acceptPromptSuggestion(1);
+}
+
+globalThis.probe_prompt_changed = function(args: { prompt_type: string; input: string }): boolean {
+    if (args.prompt_type === "picker" && args.input === "ab") {
+        inspectAndAccept();
+    } else if (args.prompt_type === "closer" && args.input === "x") {
+        editor.closePrompt("closer");
+    }
+    return true;
+};
+
+globalThis.probe_prompt_confirmed = function(args: {
+    prompt_type: string;
+    input: string;
+    selected_index: number | null;
+}): boolean {
+    if (args.prompt_type === "picker") {
+        editor.setStatus(`state=${observed} confirmed=${args.selected_index}:${args.input}`);
+        editor.startPrompt("Close me: ", "closer");
+    }
+    return true;
+};
+
+globalThis.probe_prompt_cancelled = function(args: { prompt_type: string; input: string }): boolean {
+    if (args.prompt_type === "closer") {
+        editor.setStatus(`cancelled=${args.input}`);
+    }
+    return true;
+};
+
+editor.on("prompt_changed", "probe_prompt_changed");
+editor.on("prompt_confirmed", "probe_prompt_confirmed");
+editor.on("prompt_cancelled", "probe_prompt_cancelled");
+
+editor.registerCommand(
+    "Probe Prompt",
+    "Open a prompt driven by the plugin",
+    "probe_prompt_start",
+    "normal"
+);
+"#;
+
+    let test_plugin_path = plugins_dir.join("test_prompt_control.ts");
+    fs::write(&test_plugin_path, test_plugin).unwrap();
+
+    let mut harness =
+        EditorTestHarness::with_config_and_working_dir(80, 24, Default::default(), project_root)
+            .unwrap();
+    harness.render().unwrap();
+
+    harness
+        .send_key(KeyCode::Char('p'), KeyModifiers::CONTROL)
+        .unwrap();
+    harness.type_text("Probe Prompt").unwrap();
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    harness.wait_until(|h| h.editor().is_prompting()).unwrap();
+
+    harness.type_text("ab").unwrap();
+    harness
+        .wait_until(|h| {
+            h.editor()
+                .get_status_message()
+                .is_some_and(|s| s.starts_with("state="))
+        })
+        .unwrap();
+    assert_eq!(
+        harness.editor().get_status_message().unwrap(),
+        "state=true:picker:Pick: :ab:2:0 confirmed=1:ab"
+    );
+
+    // The follow-up prompt is closed by the plugin, firing the cancelled hook
+    harness.wait_until(|h| h.editor().is_prompting()).unwrap();
+    harness.type_text("x").unwrap();
+    harness
+        .wait_until(|h| {
+            h.editor()
+                .get_status_message()
+                .is_some_and(|s| s.starts_with("cancelled="))
+        })
+        .unwrap();
+    assert!(!harness.editor().is_prompting());
+    assert_eq!(
+        harness.editor().get_status_message().unwrap(),
+        "cancelled=x"
+    );
+}
@@ -0,0 +1,198 @@
+//! E2E tests for plugins reporting the progress of long operations
+
+use crate::common::harness::{layout, EditorTestHarness};
+use crossterm::event::{KeyCode, KeyModifiers};
+use fresh::view::progress::STALE_TIMEOUT;
+use std::fs;
+use std::path::PathBuf;
+use tempfile::TempDir;
+
+/// Plugin with commands to start, advance and finish an "Indexing" entry,
+/// and to start a "Scanning" entry it never touches again
+const PROGRESS_PLUGIN: &str = r###"
+const editor = getEditor();
+let indexing: ProgressHandle | null = null;
+
+globalThis.onIndexCancelled = function(data: { progress_id: number }): void {
+    editor.setStatus(`cancelled ${data.progress_id === indexing!.id}`);
+};
+
+globalThis.index_start = async function(): Promise<void> {
+    indexing = await editor.startProgress({ title: "Indexing", cancellable: true });
+    indexing.onCancel("onIndexCancelled");
+    editor.setStatus("started");
+};
+
+globalThis.index_half = function(): void {
+    indexing!.update(50);
+    indexing!.update("lib.rs");
+    editor.setStatus("halfway");
+};
+
+globalThis.index_done = function(): void {
+    editor.setStatus(`done ${indexing!.finish()} ${indexing!.finish()}`);
+};
+
+globalThis.scan_start = async function(): Promise<void> {
+    await editor.startProgress({ title: "Scanning" });
+    editor.setStatus("scanning");
+};
+
+editor.registerCommand("Index Start", "Start indexing", "index_start", "normal");
+editor.registerCommand("Index Half", "Index half", "index_half", "normal");
+editor.registerCommand("Index Done", "Finish indexing", "index_done", "normal");
+editor.registerCommand("Scan Start", "Start scanning", "scan_start", "normal");
+editor.setStatus("progress plugin ready");
+"###;
+
+fn setup() -> (TempDir, EditorTestHarness) {
+    let temp_dir = TempDir::new().unwrap();
+    let project_dir: PathBuf = temp_dir.path().join("project");
+    let plugins_dir = project_dir.join("plugins");
+    fs::create_dir_all(&plugins_dir).unwrap();
+    fs::write(plugins_dir.join("progress_demo.ts"), PROGRESS_PLUGIN).unwrap();
+    let mut harness =
+        EditorTestHarness::with_config_and_working_dir(160, 24, Default::default(), project_dir)
+            .unwrap();
+    harness
+        .wait_until(|h| h.get_status_bar().contains("progress plugin ready"))
+        .unwrap();
+    (temp_dir, harness)
+}
+
+fn run_command(harness: &mut EditorTestHarness, name: &str) {
+    harness
+        .send_key(KeyCode::Char('p'), KeyModifiers::CONTROL)
+        .unwrap();
+    harness.type_text(name).unwrap();
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+}
+
+/// Run a plugin command and wait for the status it sets when it's done
+fn run_plugin_command(harness: &mut EditorTestHarness, name: &str, status: &str) {
+    run_command(harness, name);
+    harness
+        .wait_until(|h| {
+            h.editor()
+                .get_status_message()
+                .is_some_and(|m| m.contains(status))
+        })
+        .unwrap();
+    harness.render().unwrap();
+}
+
+/// A single entry shows in the status bar, with its percentage and message,
+/// until the plugin finishes it
+#[test]
+fn test_progress_lifecycle_in_status_bar() {
+    let (_temp_dir, mut harness) = setup();
+
+    run_plugin_command(&mut harness, "Index Start", "started");
+    assert_eq!(harness.editor().progress().entries().len(), 1);
+    harness.assert_screen_contains("Indexing");
+    assert!(harness.get_status_bar().contains("Indexing"));
+
+    run_plugin_command(&mut harness, "Index Half", "halfway");
+    assert!(
+        harness.get_status_bar().contains("50% Indexing: lib.rs"),
+        "{}",
+        harness.get_status_bar()
+    );
+
+    // Finishing twice is harmless
+    run_plugin_command(&mut harness, "Index Done", "done true false");
+    assert!(harness.editor().progress().is_empty());
+    assert!(!harness.get_status_bar().contains("Indexing"));
+}
+
+/// Clicking the entry in the status bar cancels it: the plugin's handler
+/// runs and the entry shows as cancelling until the plugin finishes it
+#[test]
+fn test_progress_cancel_runs_plugin_handler() {
+    let (_temp_dir, mut harness) = setup();
+    run_plugin_command(&mut harness, "Index Start", "started");
+    run_plugin_command(&mut harness, "Index Half", "halfway");
+
+    let status_bar = harness.get_status_bar();
+    let start = status_bar.find("50% Indexing").unwrap();
+    let col = status_bar[..start].chars().count() as u16;
+    let row = layout::status_bar_row(harness.terminal_height()) as u16;
+    harness.mouse_click(col + 1, row).unwrap();
+    harness
+        .wait_until(|h| h.get_status_bar().contains("cancelled true"))
+        .unwrap();
+
+    let entry = &harness.editor().progress().entries()[0];
+    assert!(entry.cancelling);
+    harness.render().unwrap();
+    assert!(harness.get_status_bar().contains("(cancelling…)"));
+
+    // Cancelling again does nothing more
+    run_command(&mut harness, "Cancel Operation");
+    harness
+        .wait_until(|h| {
+            h.editor()
+                .get_status_message()
+                .is_some_and(|m| m.contains("No operation in progress can be cancelled"))
+        })
+        .unwrap();
+
+    run_plugin_command(&mut harness, "Index Done", "done true");
+    assert!(harness.editor().progress().is_empty());
+}
+
+/// With several entries, each shows as a notification, with a Cancel
+/// button when it can be cancelled
+#[test]
+fn test_several_progress_entries_show_as_notifications() {
+    let (_temp_dir, mut harness) = setup();
+    run_plugin_command(&mut harness, "Index Start", "started");
+    run_plugin_command(&mut harness, "Scan Start", "scanning");
+
+    let visible: Vec<_> = harness
+        .editor()
+        .notifications()
+        .visible()
+        .map(|n| (n.message.clone(), n.actions.len()))
+        .collect();
+    assert_eq!(visible.len(), 2, "{:?}", visible);
+    assert!(visible[0].0.ends_with("Indexing") && visible[0].1 == 1);
+    assert!(visible[1].0.ends_with("Scanning") && visible[1].1 == 0);
+    harness.assert_screen_contains("Cancel");
+
+    // Back to one entry, it moves to the status bar
+    run_plugin_command(&mut harness, "Index Done", "done true");
+    assert_eq!(harness.editor().notifications().visible().count(), 0);
+    assert!(harness.get_status_bar().contains("Scanning"));
+}
+
+/// An entry the plugin stops updating is finished for it with a warning
+#[test]
+fn test_stale_progress_is_finished_with_warning() {
+    let (_temp_dir, mut harness) = setup();
+    run_plugin_command(&mut harness, "Scan Start", "scanning");
+    run_plugin_command(&mut harness, "Index Start", "started");
+
+    harness.advance_time(STALE_TIMEOUT / 2);
+    run_plugin_command(&mut harness, "Index Half", "halfway");
+    harness.advance_time(STALE_TIMEOUT / 2);
+    harness.process_async_and_render().unwrap();
+
+    // Only the entry updated since is left
+    let titles: Vec<_> = harness
+        .editor()
+        .progress()
+        .entries()
+        .iter()
+        .map(|entry| entry.title.clone())
+        .collect();
+    assert_eq!(titles, vec!["Indexing"]);
+    assert!(harness
+        .editor()
+        .notifications()
+        .history()
+        .any(|n| n.message
+            == "Scanning stopped: plugin progress_demo no longer reports its progress"));
+}
@@ -0,0 +1,80 @@
+//! E2E tests for plugin edits to files matching `files.protected_globs`
+
+use crate::common::harness::EditorTestHarness;
+use crossterm::event::{KeyCode, KeyModifiers};
+use fresh::config::Config;
+use std::fs;
+
+/// Plugin that edits the active buffer on a command and lists the edits
+/// refused in the status bar
+const EDIT_PLUGIN: &str = r###"
+const editor = getEditor();
+const rejected: string[] = [];
+
+globalThis.protected_edit = function(): void {
+    const bufferId = editor.getActiveBufferId();
+    editor.insertText(bufferId, 0, "# ");
+    editor.applyEdits(bufferId, [{ start: 0, end: 0, text: "X" }]);
+};
+
+globalThis.on_edit_rejected = function(args: { buffer_id: number; command: string; reason: string; rule: string }): void {
+    rejected.push(`${args.command} ${args.reason} ${args.rule}`);
+    editor.setStatus(rejected.join(" | "));
+};
+
+editor.on("edit_rejected", "on_edit_rejected");
+editor.registerCommand("Protected: Edit", "Edit the active buffer", "protected_edit", "normal");
+"###;
+
+fn run_command(harness: &mut EditorTestHarness, name: &str) {
+    harness
+        .send_key(KeyCode::Char('p'), KeyModifiers::CONTROL)
+        .unwrap();
+    harness.type_text(name).unwrap();
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    harness.render().unwrap();
+}
+
+/// Plugin edits to a protected file are refused with an `edit_rejected`
+/// hook until the user unlocks it
+#[test]
+fn test_plugin_edits_rejected_until_unlocked() {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let project_root = temp_dir.path().join("project_root");
+    let plugins_dir = project_root.join("plugins");
+    fs::create_dir_all(&plugins_dir).unwrap();
+    fs::write(plugins_dir.join("protected_edit.ts"), EDIT_PLUGIN).unwrap();
+    let file = project_root.join("id_rsa");
+    fs::write(&file, "key\n").unwrap();
+
+    let mut harness =
+        EditorTestHarness::with_config_and_working_dir(100, 24, Config::default(), project_root)
+            .unwrap();
+    harness.open_file(&file).unwrap();
+    harness.render().unwrap();
+
+    run_command(&mut harness, "Protected: Edit");
+    let expected = "insert_text protected **/id_rsa* | apply_edits protected **/id_rsa*";
+    harness
+        .wait_until(|h| h.editor().get_status_message().map(String::as_str) == Some(expected))
+        .unwrap();
+    assert_eq!(harness.get_buffer_content().unwrap(), "key\n");
+
+    run_command(&mut harness, "Unlock Protected File");
+    harness.type_text("y").unwrap();
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+
+    run_command(&mut harness, "Protected: Edit");
+    harness
+        .wait_until(|h| {
+            h.get_buffer_content()
+                .is_some_and(|text| text == "X# key\n")
+        })
+        .unwrap();
+    // The file itself waits for a save
+    assert_eq!(fs::read_to_string(&file).unwrap(), "key\n");
+}
@@ -0,0 +1,86 @@
+//! E2E tests for the views a split keeps of buffers it isn't showing
+
+use crate::common::harness::EditorTestHarness;
+use fresh::config::Config;
+use std::fs;
+
+/// Plugin that edits a.txt the second time b.txt is activated
+const EDIT_PLUGIN: &str = r###"
+const editor = getEditor();
+let activations = 0;
+
+globalThis.edit_inactive = function(args: { buffer_id: number }): void {
+    const active = editor.listBuffers().find((b) => b.id === args.buffer_id);
+    if (!active || !active.path.endsWith("b.txt")) {
+        return;
+    }
+    activations++;
+    if (activations !== 2) {
+        return;
+    }
+    const other = editor.listBuffers().find((b) => b.path.endsWith("a.txt"));
+    if (other) {
+        editor.insertText(other.id, 0, "edited ");
+        editor.setStatus("edited a.txt");
+    }
+};
+
+editor.on("buffer_activated", "edit_inactive");
+editor.setStatus("edit plugin ready");
+"###;
+
+fn laid_out(harness: &EditorTestHarness) -> usize {
+    harness.editor().active_line_layout().lines_laid_out()
+}
+
+/// An edit a plugin makes to a buffer while another one is shown drops the
+/// kept view of the edited buffer, which is laid out afresh when shown
+#[test]
+fn test_plugin_edit_to_inactive_buffer_invalidates_kept_view() {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let project_root = temp_dir.path().join("project_root");
+    let plugins_dir = project_root.join("plugins");
+    fs::create_dir_all(&plugins_dir).unwrap();
+    fs::write(plugins_dir.join("edit.ts"), EDIT_PLUGIN).unwrap();
+    let a = project_root.join("a.txt");
+    let b = project_root.join("b.txt");
+    fs::write(&a, "alpha\n".repeat(100)).unwrap();
+    fs::write(&b, "beta\n".repeat(100)).unwrap();
+
+    let mut harness =
+        EditorTestHarness::with_config_and_working_dir(80, 24, Config::default(), project_root)
+            .unwrap();
+    harness
+        .wait_until(|h| {
+            h.editor()
+                .get_status_message()
+                .is_some_and(|s| s == "edit plugin ready")
+        })
+        .unwrap();
+    harness.open_file(&a).unwrap();
+    let a_id = harness.editor().active_buffer();
+    harness.open_file(&b).unwrap();
+    let b_id = harness.editor().active_buffer();
+
+    // Back to the unchanged a.txt: nothing is laid out again
+    harness.editor_mut().switch_buffer(a_id);
+    let before = laid_out(&harness);
+    harness.render().unwrap();
+    assert_eq!(laid_out(&harness), before);
+
+    // Showing b.txt again makes the plugin edit a.txt in the background
+    harness.editor_mut().switch_buffer(b_id);
+    harness
+        .wait_until(|h| {
+            h.editor()
+                .get_status_message()
+                .is_some_and(|s| s == "edited a.txt")
+        })
+        .unwrap();
+
+    harness.editor_mut().switch_buffer(a_id);
+    let before = laid_out(&harness);
+    harness.render().unwrap();
+    assert!(laid_out(&harness) > before);
+    harness.assert_screen_contains("edited alpha");
+}
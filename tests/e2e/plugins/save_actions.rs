@@ -0,0 +1,102 @@
+//! E2E tests for save actions provided by plugins
+
+use crate::common::harness::EditorTestHarness;
+use crossterm::event::{KeyCode, KeyModifiers};
+use fresh::config::{Config, LanguageConfig, SaveActionFailure};
+use std::fs;
+
+/// Plugin with a save action that puts a header line above the text, and
+/// fails if the step before it left trailing whitespace
+const HEADER_PLUGIN: &str = r###"
+const editor = getEditor();
+
+globalThis.header_add = async function(args: { request_id: number; buffer_id: number }): Promise<void> {
+    const length = editor.getBufferLength(args.buffer_id);
+    const text = await editor.getBufferText(args.buffer_id, 0, length);
+    if (text !== text.trimEnd()) {
+        editor.finishSaveAction(args.request_id, "trailing whitespace left");
+        return;
+    }
+    editor.applyEdits(args.buffer_id, [{ start: 0, end: 0, text: "# header\n" }]);
+    editor.finishSaveAction(args.request_id, null);
+};
+
+editor.on("save_action:header.add", "header_add");
+editor.setStatus("header plugin ready");
+"###;
+
+/// A plugin step runs between the built-in steps, and all of their edits
+/// are saved and undo as one step
+#[test]
+fn test_plugin_save_action_runs_in_order() {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let project_root = temp_dir.path().join("project_root");
+    let plugins_dir = project_root.join("plugins");
+    fs::create_dir_all(&plugins_dir).unwrap();
+    fs::write(plugins_dir.join("header.ts"), HEADER_PLUGIN).unwrap();
+    let file = project_root.join("doc.txt");
+    fs::write(&file, "text  ").unwrap();
+
+    let mut config = Config::default();
+    config.languages.insert(
+        "plaintext".to_string(),
+        LanguageConfig {
+            extensions: vec!["txt".to_string()],
+            filenames: vec![],
+            grammar: "plaintext".to_string(),
+            comment_prefix: None,
+            auto_indent: false,
+            highlighter: Default::default(),
+            textmate_grammar: None,
+            show_whitespace_tabs: true,
+            use_tabs: false,
+            tab_size: None,
+            formatter: None,
+            format_on_save: false,
+            on_save: vec![],
+            indent_rules: None,
+            on_type_formatting: false,
+            rulers: None,
+            auto_complete: true,
+            completion_trigger_characters: vec![],
+            save_actions: vec![
+                "trim_trailing_whitespace".to_string(),
+                "plugin:header.add".to_string(),
+                "ensure_final_newline".to_string(),
+            ],
+            save_actions_on_failure: SaveActionFailure::Abort,
+            save_actions_timeout_ms: 5000,
+        },
+    );
+
+    let mut harness =
+        EditorTestHarness::with_config_and_working_dir(80, 24, config, project_root).unwrap();
+    harness
+        .wait_until(|h| {
+            h.editor()
+                .get_status_message()
+                .is_some_and(|s| s == "header plugin ready")
+        })
+        .unwrap();
+    harness.open_file(&file).unwrap();
+    harness.render().unwrap();
+    let buffer_id = harness.editor().active_buffer();
+
+    harness
+        .send_key(KeyCode::Char('s'), KeyModifiers::CONTROL)
+        .unwrap();
+    harness
+        .wait_until(|h| {
+            !h.editor().is_running_save_actions(buffer_id)
+                && !h.editor().is_buffer_saving(buffer_id)
+        })
+        .unwrap();
+
+    assert_eq!(fs::read_to_string(&file).unwrap(), "# header\ntext\n");
+    harness.assert_buffer_content("# header\ntext\n");
+
+    harness
+        .send_key(KeyCode::Char('z'), KeyModifiers::CONTROL)
+        .unwrap();
+    harness.assert_buffer_content("text  ");
+}
@@ -0,0 +1,207 @@
+//! E2E tests for the split lifecycle hooks
+
+use crate::common::harness::EditorTestHarness;
+use crossterm::event::{KeyCode, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+use fresh::config::Config;
+use std::fs;
+use tempfile::TempDir;
+
+/// Plugin that lists every split hook in the status bar, and gives each
+/// split the user makes a quarter of its container
+const SPLIT_LOG_PLUGIN: &str = r###"
+const editor = getEditor();
+const log: string[] = [];
+
+function record(entry: string): void {
+    log.push(entry);
+    editor.setStatus(log.join(" | "));
+}
+
+globalThis.on_split_created = function(args: { split_id: number; parent_id: number; direction: string; by_plugin: boolean }): void {
+    record(`created ${args.split_id} in ${args.parent_id} ${args.direction} plugin=${args.by_plugin}`);
+    if (!args.by_plugin) {
+        editor.setSplitRatio(args.parent_id, 0.25);
+    }
+};
+
+globalThis.on_split_closed = function(args: { split_id: number; by_plugin: boolean }): void {
+    record(`closed ${args.split_id} plugin=${args.by_plugin}`);
+};
+
+globalThis.on_split_focused = function(args: { split_id: number; previous_split_id: number; by_plugin: boolean }): void {
+    record(`focused ${args.split_id} from ${args.previous_split_id} plugin=${args.by_plugin}`);
+};
+
+globalThis.on_split_resized = function(args: { split_id: number; ratio: number; by_plugin: boolean }): void {
+    record(`resized ${args.split_id} ${args.ratio.toFixed(2)} plugin=${args.by_plugin}`);
+};
+
+globalThis.on_layout_restored = function(args: { source: string; active_split_id: number }): void {
+    record(`restored ${args.source} ${args.active_split_id}`);
+};
+
+editor.on("split_created", "on_split_created");
+editor.on("split_closed", "on_split_closed");
+editor.on("split_focused", "on_split_focused");
+editor.on("split_resized", "on_split_resized");
+editor.on("layout_restored", "on_layout_restored");
+"###;
+
+fn split_log_harness(temp_dir: &TempDir) -> EditorTestHarness {
+    let project_root = temp_dir.path().join("project_root");
+    let plugins_dir = project_root.join("plugins");
+    fs::create_dir_all(&plugins_dir).unwrap();
+    fs::write(plugins_dir.join("split_log.ts"), SPLIT_LOG_PLUGIN).unwrap();
+    fs::write(project_root.join("doc.txt"), "text\n").unwrap();
+
+    let mut harness = EditorTestHarness::with_config_and_working_dir(
+        80,
+        24,
+        Config::default(),
+        project_root.clone(),
+    )
+    .unwrap();
+    harness.open_file(&project_root.join("doc.txt")).unwrap();
+    harness.render().unwrap();
+    harness
+}
+
+fn run_command(harness: &mut EditorTestHarness, name: &str) {
+    harness
+        .send_key(KeyCode::Char('p'), KeyModifiers::CONTROL)
+        .unwrap();
+    harness.type_text(name).unwrap();
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    harness.render().unwrap();
+}
+
+fn run_command_with_input(harness: &mut EditorTestHarness, name: &str, input: &str) {
+    run_command(harness, name);
+    harness.type_text(input).unwrap();
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    harness.render().unwrap();
+}
+
+/// Wait until the plugin has logged exactly `entries`
+fn wait_for_log(harness: &mut EditorTestHarness, entries: &[String]) {
+    let expected = entries.join(" | ");
+    harness
+        .wait_until(|h| h.editor().get_status_message() == Some(&expected))
+        .unwrap();
+}
+
+fn mouse(harness: &mut EditorTestHarness, kind: MouseEventKind, column: u16, row: u16) {
+    harness
+        .send_mouse(MouseEvent {
+            kind,
+            column,
+            row,
+            modifiers: KeyModifiers::empty(),
+        })
+        .unwrap();
+}
+
+/// Split the active split, returning the ids of the split, the new split and
+/// their container, and the entries the plugin logs for it, the last being
+/// its own resize
+fn split_vertically(harness: &mut EditorTestHarness) -> (usize, usize, usize, Vec<String>) {
+    let first = harness.editor().get_active_split().0;
+    run_command(harness, "Split Vertical");
+    let second = harness.editor().get_active_split().0;
+    let container = harness.editor().get_separator_areas()[0].0 .0;
+    let log = vec![
+        format!("created {second} in {container} vertical plugin=false"),
+        format!("focused {second} from {first} plugin=false"),
+        format!("resized {container} 0.25 plugin=true"),
+    ];
+    wait_for_log(harness, &log);
+    (first, second, container, log)
+}
+
+/// Splitting, moving focus and closing fire one hook per change in order,
+/// and the resize the plugin makes in answer is marked as its own
+#[test]
+fn test_split_hook_sequence() {
+    let temp_dir = TempDir::new().unwrap();
+    let mut harness = split_log_harness(&temp_dir);
+    let (first, second, _, mut log) = split_vertically(&mut harness);
+
+    run_command(&mut harness, "Next Split");
+    log.push(format!("focused {first} from {second} plugin=false"));
+    wait_for_log(&mut harness, &log);
+
+    run_command(&mut harness, "Close Split");
+    log.push(format!("closed {first} plugin=false"));
+    log.push(format!("focused {second} from {first} plugin=false"));
+    wait_for_log(&mut harness, &log);
+}
+
+/// Dragging a separator fires `split_resized` once, when the drag ends
+#[test]
+fn test_split_resized_fires_once_per_drag() {
+    let temp_dir = TempDir::new().unwrap();
+    let mut harness = split_log_harness(&temp_dir);
+    let (_, _, container, mut log) = split_vertically(&mut harness);
+    harness.render().unwrap();
+
+    let (_, _, x, y, length) = harness.editor().get_separator_areas()[0];
+    let row = y + length / 2;
+    mouse(
+        &mut harness,
+        MouseEventKind::Down(MouseButton::Left),
+        x,
+        row,
+    );
+    for step in 1..=10 {
+        mouse(
+            &mut harness,
+            MouseEventKind::Drag(MouseButton::Left),
+            x + step * 2,
+            row,
+        );
+        harness.process_async_and_render().unwrap();
+    }
+    // Nothing is reported while the separator is held
+    assert_eq!(
+        harness.editor().get_status_message().unwrap(),
+        &log.join(" | ")
+    );
+
+    mouse(
+        &mut harness,
+        MouseEventKind::Up(MouseButton::Left),
+        x + 20,
+        row,
+    );
+    let ratio = harness
+        .editor()
+        .get_split_ratio(fresh::model::event::SplitId(container))
+        .unwrap();
+    log.push(format!("resized {container} {ratio:.2} plugin=false"));
+    wait_for_log(&mut harness, &log);
+}
+
+/// Switching to a saved layout fires `layout_restored` instead of a hook
+/// for each split it rebuilds
+#[test]
+fn test_layout_switch_fires_layout_restored() {
+    let temp_dir = TempDir::new().unwrap();
+    let mut harness = split_log_harness(&temp_dir);
+    let (first, second, _, mut log) = split_vertically(&mut harness);
+
+    run_command_with_input(&mut harness, "Save Layout As", "two");
+    run_command(&mut harness, "Close Split");
+    log.push(format!("closed {second} plugin=false"));
+    log.push(format!("focused {first} from {second} plugin=false"));
+    wait_for_log(&mut harness, &log);
+
+    run_command_with_input(&mut harness, "Switch Layout", "two");
+    assert_eq!(harness.editor().get_split_count(), 2);
+    let active = harness.editor().get_active_split().0;
+    log.push(format!("restored layout {active}"));
+    wait_for_log(&mut harness, &log);
+}
@@ -0,0 +1,55 @@
+//! E2E tests for the startup profile
+
+use crate::common::harness::EditorTestHarness;
+use fresh::services::startup_profile;
+use std::fs;
+use tempfile::TempDir;
+
+/// Creating an editor records its phases, with one entry per loaded plugin
+/// nested under plugin loading
+#[test]
+fn test_startup_profile_lists_phases_and_plugins() {
+    let temp_dir = TempDir::new().unwrap();
+    let project_dir = temp_dir.path().join("project");
+    let plugins_dir = project_dir.join("plugins");
+    fs::create_dir_all(&plugins_dir).unwrap();
+    fs::write(
+        plugins_dir.join("profiled_plugin.ts"),
+        "const editor = getEditor();\neditor.setStatus(\"profiled plugin loaded\");\n",
+    )
+    .unwrap();
+
+    startup_profile::enable(None);
+    let mut harness =
+        EditorTestHarness::with_config_and_working_dir(80, 24, Default::default(), project_dir)
+            .unwrap();
+    let report = startup_profile::finish().expect("profile was enabled");
+    harness.render().unwrap();
+
+    for name in ["theme", "plugin_runtime", "plugins"] {
+        assert!(report.has_phase(name), "missing {}:\n{}", name, report);
+    }
+
+    let plugins = report
+        .phases
+        .iter()
+        .position(|p| p.name == "plugins")
+        .unwrap();
+    let plugin = report
+        .phases
+        .iter()
+        .position(|p| p.name == "plugin profiled_plugin")
+        .unwrap_or_else(|| panic!("no per-plugin entry:\n{}", report));
+    assert!(plugin > plugins);
+    assert_eq!(
+        report.phases[plugin].depth,
+        report.phases[plugins].depth + 1
+    );
+    assert!(report.phases[plugins]
+        .counts
+        .iter()
+        .any(|(label, count)| *label == "loaded" && *count >= 1));
+
+    let text = report.to_string();
+    assert!(text.contains("plugin profiled_plugin"));
+}
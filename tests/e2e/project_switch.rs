@@ -0,0 +1,92 @@
+//! E2E tests for state carried across a project switch
+
+use crate::common::harness::EditorTestHarness;
+use crossterm::event::{KeyCode, KeyModifiers};
+use fresh::config::Config;
+use fresh::config_io::DirectoryContext;
+use tempfile::TempDir;
+
+/// A modified untitled buffer and the clipboard survive switching projects,
+/// alongside the new project's files
+#[test]
+fn test_untitled_buffer_and_clipboard_survive_project_switch() {
+    let temp = TempDir::new().unwrap();
+    let dir_context = DirectoryContext::for_testing(temp.path());
+    let first = temp.path().join("first");
+    let second = temp.path().join("second");
+    std::fs::create_dir_all(&first).unwrap();
+    std::fs::create_dir_all(&second).unwrap();
+    let second = second.canonicalize().unwrap();
+
+    let mut harness = EditorTestHarness::with_shared_dir_context(
+        80,
+        24,
+        Config::default(),
+        first,
+        dir_context.clone(),
+    )
+    .unwrap();
+    harness.type_text("scratch notes").unwrap();
+    harness
+        .editor_mut()
+        .set_clipboard_for_test("copied text".to_string());
+
+    harness.editor_mut().change_working_dir(second.clone());
+    assert_eq!(harness.switch_project(dir_context).unwrap(), second);
+
+    // Still modified, so quitting asks about it
+    harness.assert_buffer_content("scratch notes");
+    assert!(harness.editor().active_state().buffer.is_modified());
+
+    harness.send_key(KeyCode::End, KeyModifiers::NONE).unwrap();
+    harness
+        .send_key(KeyCode::Char('v'), KeyModifiers::CONTROL)
+        .unwrap();
+    harness.assert_buffer_content("scratch notescopied text");
+}
+
+/// Files are left to the project sessions: the untitled buffer comes along
+/// without taking the place of the new project's active file
+#[test]
+fn test_project_switch_keeps_the_sessions_active_file() {
+    let temp = TempDir::new().unwrap();
+    let dir_context = DirectoryContext::for_testing(temp.path());
+    let first = temp.path().join("first");
+    let second = temp.path().join("second");
+    std::fs::create_dir_all(&first).unwrap();
+    std::fs::create_dir_all(&second).unwrap();
+    let file = second.join("main.txt");
+    std::fs::write(&file, "second project\n").unwrap();
+
+    // The second project's session has main.txt open
+    {
+        let mut harness = EditorTestHarness::with_shared_dir_context(
+            80,
+            24,
+            Config::default(),
+            second.clone(),
+            dir_context.clone(),
+        )
+        .unwrap();
+        harness.open_file(&file).unwrap();
+        harness.editor_mut().save_session().unwrap();
+    }
+
+    let mut harness = EditorTestHarness::with_shared_dir_context(
+        80,
+        24,
+        Config::default(),
+        first,
+        dir_context.clone(),
+    )
+    .unwrap();
+    harness.type_text("scratch").unwrap();
+
+    harness.editor_mut().change_working_dir(second);
+    harness.switch_project(dir_context).unwrap();
+
+    harness.assert_buffer_content("second project\n");
+    harness.editor_mut().next_buffer();
+    harness.assert_buffer_content("scratch");
+    assert!(harness.editor().active_state().buffer.is_modified());
+}
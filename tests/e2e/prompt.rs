@@ -801,3 +801,93 @@ fn test_open_file_prompt_shows_completions_immediately() {
     let screen = harness.screen_to_string();
     println!("Screen after opening prompt:\n{}", screen);
 }
+
+/// Test that a plugin can only close its own prompt type
+#[test]
+fn test_plugin_close_prompt_requires_matching_type() {
+    use fresh::services::plugins::api::PluginCommand;
+    let mut harness = EditorTestHarness::new(80, 24).unwrap();
+
+    harness
+        .editor_mut()
+        .handle_plugin_command(PluginCommand::StartPrompt {
+            label: "Grep: ".to_string(),
+            prompt_type: "git-grep".to_string(),
+        })
+        .unwrap();
+    assert!(harness.editor().is_prompting());
+
+    // A close request for a different prompt type is ignored
+    harness
+        .editor_mut()
+        .handle_plugin_command(PluginCommand::ClosePrompt {
+            prompt_type: Some("git-find-file".to_string()),
+        })
+        .unwrap();
+    assert!(harness.editor().is_prompting());
+
+    harness
+        .editor_mut()
+        .handle_plugin_command(PluginCommand::ClosePrompt {
+            prompt_type: Some("git-grep".to_string()),
+        })
+        .unwrap();
+    assert!(!harness.editor().is_prompting());
+}
+
+/// Test that plugins can't close prompts the user opened
+#[test]
+fn test_plugin_close_prompt_ignores_builtin_prompts() {
+    use crossterm::event::{KeyCode, KeyModifiers};
+    use fresh::services::plugins::api::PluginCommand;
+    let mut harness = EditorTestHarness::new(80, 24).unwrap();
+
+    harness
+        .send_key(KeyCode::Char('f'), KeyModifiers::CONTROL)
+        .unwrap();
+    assert!(harness.editor().is_prompting());
+
+    harness
+        .editor_mut()
+        .handle_plugin_command(PluginCommand::ClosePrompt { prompt_type: None })
+        .unwrap();
+    assert!(harness.editor().is_prompting());
+}
+
+/// Test accepting a plugin prompt suggestion by index
+#[test]
+fn test_plugin_accept_prompt_suggestion() {
+    use fresh::input::commands::Suggestion;
+    use fresh::services::plugins::api::PluginCommand;
+    let mut harness = EditorTestHarness::new(80, 24).unwrap();
+
+    harness
+        .editor_mut()
+        .handle_plugin_command(PluginCommand::StartPrompt {
+            label: "Pick: ".to_string(),
+            prompt_type: "picker".to_string(),
+        })
+        .unwrap();
+    harness
+        .editor_mut()
+        .handle_plugin_command(PluginCommand::SetPromptSuggestions {
+            suggestions: vec![
+                Suggestion::new("one".to_string()),
+                Suggestion::new("two".to_string()),
+            ],
+        })
+        .unwrap();
+
+    // Out-of-range indices leave the prompt open
+    harness
+        .editor_mut()
+        .handle_plugin_command(PluginCommand::AcceptPromptSuggestion { index: 2 })
+        .unwrap();
+    assert!(harness.editor().is_prompting());
+
+    harness
+        .editor_mut()
+        .handle_plugin_command(PluginCommand::AcceptPromptSuggestion { index: 1 })
+        .unwrap();
+    assert!(!harness.editor().is_prompting());
+}
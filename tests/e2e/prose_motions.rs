@@ -0,0 +1,133 @@
+//! E2E tests for the paragraph and sentence motions on prose
+
+use crate::common::fixtures::TestFixture;
+use crate::common::harness::EditorTestHarness;
+use fresh::services::plugins::api::PluginCommand;
+
+const PROSE: &str = "\
+The editor reads prose, e.g. notes and docs. It moves by sentence!
+Sentences may span lines. \"Does it?\" she asked.
+
+  A second paragraph, indented. It has two sentences.
+
+Last one...
+";
+
+fn execute_action(harness: &mut EditorTestHarness, name: &str) {
+    harness
+        .editor_mut()
+        .handle_plugin_command(PluginCommand::ExecuteAction {
+            action_name: name.to_string(),
+        })
+        .unwrap();
+    harness.render().unwrap();
+}
+
+fn offset(text: &str, needle: &str) -> usize {
+    text.find(needle).unwrap()
+}
+
+#[test]
+fn test_sentence_motions() {
+    let mut harness = EditorTestHarness::new(100, 24).unwrap();
+    let _fixture = harness.load_buffer_from_text(PROSE).unwrap();
+    harness.render().unwrap();
+
+    // "e.g." is an abbreviation, the question in quotes goes on
+    execute_action(&mut harness, "move_sentence_forward");
+    assert_eq!(harness.cursor_position(), offset(PROSE, "It moves"));
+    execute_action(&mut harness, "move_sentence_forward");
+    assert_eq!(harness.cursor_position(), offset(PROSE, "Sentences"));
+    execute_action(&mut harness, "move_sentence_forward");
+    assert_eq!(harness.cursor_position(), offset(PROSE, "\"Does"));
+    // Into the next paragraph, past its indentation
+    execute_action(&mut harness, "move_sentence_forward");
+    assert_eq!(harness.cursor_position(), offset(PROSE, "A second"));
+
+    execute_action(&mut harness, "move_sentence_backward");
+    assert_eq!(harness.cursor_position(), offset(PROSE, "\"Does"));
+    execute_action(&mut harness, "move_sentence_backward");
+    assert_eq!(harness.cursor_position(), offset(PROSE, "Sentences"));
+}
+
+#[test]
+fn test_paragraph_motions() {
+    let mut harness = EditorTestHarness::new(100, 24).unwrap();
+    let _fixture = harness.load_buffer_from_text(PROSE).unwrap();
+    harness.render().unwrap();
+
+    execute_action(&mut harness, "move_paragraph_forward");
+    assert_eq!(harness.cursor_position(), offset(PROSE, "A second"));
+    execute_action(&mut harness, "move_paragraph_forward");
+    assert_eq!(harness.cursor_position(), offset(PROSE, "Last one"));
+    execute_action(&mut harness, "move_paragraph_forward");
+    assert_eq!(harness.cursor_position(), PROSE.len());
+
+    execute_action(&mut harness, "move_paragraph_backward");
+    assert_eq!(harness.cursor_position(), offset(PROSE, "Last one"));
+    execute_action(&mut harness, "move_paragraph_backward");
+    assert_eq!(harness.cursor_position(), offset(PROSE, "A second"));
+    execute_action(&mut harness, "move_paragraph_backward");
+    assert_eq!(harness.cursor_position(), 0);
+}
+
+/// Headings and list items are paragraphs of their own in markdown
+#[test]
+fn test_paragraph_motions_markdown() {
+    let text = "# Title\nIntro text.\n\n- first item\n  continued\n- second item\n";
+    let fixture = TestFixture::new("notes.md", text).unwrap();
+    let mut harness = EditorTestHarness::new(100, 24).unwrap();
+    harness.open_file(&fixture.path).unwrap();
+    harness.render().unwrap();
+
+    execute_action(&mut harness, "move_paragraph_forward");
+    assert_eq!(harness.cursor_position(), offset(text, "Intro"));
+    execute_action(&mut harness, "move_paragraph_forward");
+    assert_eq!(harness.cursor_position(), offset(text, "- first"));
+    execute_action(&mut harness, "move_paragraph_forward");
+    assert_eq!(harness.cursor_position(), offset(text, "- second"));
+}
+
+#[test]
+fn test_select_and_delete_sentence() {
+    let mut harness = EditorTestHarness::new(100, 24).unwrap();
+    let _fixture = harness.load_buffer_from_text(PROSE).unwrap();
+    harness.render().unwrap();
+
+    execute_action(&mut harness, "move_sentence_forward");
+    execute_action(&mut harness, "select_sentence");
+    let start = offset(PROSE, "It moves");
+    let end = offset(PROSE, "Sentences");
+    assert_eq!(harness.cursor_position(), end);
+    assert_eq!(harness.get_selection_range(), Some(start..end));
+
+    // With a selection the deletion takes the selection
+    execute_action(&mut harness, "delete_sentence_forward");
+    let expected = format!("{}{}", &PROSE[..start], &PROSE[end..]);
+    assert_eq!(harness.get_buffer_content().unwrap(), expected);
+
+    execute_action(&mut harness, "delete_sentence_forward");
+    let expected = expected.replacen("Sentences may span lines. ", "", 1);
+    assert_eq!(harness.get_buffer_content().unwrap(), expected);
+}
+
+#[test]
+fn test_sentence_motions_multiple_cursors() {
+    let text = "Alpha one. Two more\nwords.\n\nAlpha three. Four\n";
+    let mut harness = EditorTestHarness::new(100, 24).unwrap();
+    let _fixture = harness.load_buffer_from_text(text).unwrap();
+    harness.render().unwrap();
+
+    execute_action(&mut harness, "select_word");
+    execute_action(&mut harness, "add_cursor_next_match");
+    execute_action(&mut harness, "move_sentence_forward");
+    execute_action(&mut harness, "delete_sentence_forward");
+    assert_eq!(
+        harness.get_buffer_content().unwrap(),
+        "Alpha one. \n\nAlpha three. \n"
+    );
+
+    // Both deletions are undone together
+    execute_action(&mut harness, "undo");
+    assert_eq!(harness.get_buffer_content().unwrap(), text);
+}
@@ -0,0 +1,107 @@
+//! E2E tests for files matching `files.protected_globs`
+
+use crate::common::harness::EditorTestHarness;
+use crossterm::event::{KeyCode, KeyModifiers};
+use fresh::config::Config;
+use tempfile::TempDir;
+
+fn run_command(harness: &mut EditorTestHarness, name: &str) {
+    harness
+        .send_key(KeyCode::Char('p'), KeyModifiers::CONTROL)
+        .unwrap();
+    harness.type_text(name).unwrap();
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    harness.render().unwrap();
+}
+
+fn answer(harness: &mut EditorTestHarness, text: &str) {
+    harness.type_text(text).unwrap();
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    harness.render().unwrap();
+}
+
+fn save(harness: &mut EditorTestHarness) {
+    harness
+        .send_key(KeyCode::Char('s'), KeyModifiers::CONTROL)
+        .unwrap();
+    harness.render().unwrap();
+}
+
+/// A protected file stays as it is until it is unlocked, and the first save
+/// after unlocking asks once, with the lines that change
+#[test]
+fn test_unlock_and_save_protected_file() {
+    let mut harness = EditorTestHarness::with_temp_project(100, 24).unwrap();
+    let file = harness.project_dir().unwrap().join(".env");
+    std::fs::write(&file, "KEY=1\n").unwrap();
+    harness.open_file(&file).unwrap();
+    harness.render().unwrap();
+    harness.assert_screen_contains("[protected]");
+
+    harness.type_text("NEW=2").unwrap();
+    harness.render().unwrap();
+    assert_eq!(harness.get_buffer_content().unwrap(), "KEY=1\n");
+
+    // Declining leaves it locked
+    run_command(&mut harness, "Unlock Protected File");
+    harness.assert_screen_contains("protected by '**/.env*'. Unlock it for editing?");
+    answer(&mut harness, "n");
+    harness.assert_screen_contains("[protected]");
+
+    run_command(&mut harness, "Unlock Protected File");
+    answer(&mut harness, "y");
+    harness.assert_screen_not_contains("[protected]");
+
+    harness.type_text("NEW=2").unwrap();
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    save(&mut harness);
+    assert!(harness.editor().is_prompting());
+    harness.assert_screen_contains("Save protected file .env (1 lines added, 0 removed)?");
+    answer(&mut harness, "y");
+    assert_eq!(std::fs::read_to_string(&file).unwrap(), "NEW=2\nKEY=1\n");
+
+    // Later saves don't ask again
+    harness.type_text("OTHER=3\n").unwrap();
+    save(&mut harness);
+    assert!(!harness.editor().is_prompting());
+    assert_eq!(
+        std::fs::read_to_string(&file).unwrap(),
+        "NEW=2\nOTHER=3\nKEY=1\n"
+    );
+}
+
+/// A negation in the config lifts the protection the built-in globs give
+#[test]
+fn test_negated_glob_leaves_file_editable() {
+    let temp_dir = TempDir::new().unwrap();
+    let project_root = temp_dir.path().join("project_root");
+    std::fs::create_dir_all(&project_root).unwrap();
+    let test_env = project_root.join(".env.test");
+    let prod_env = project_root.join(".env.production");
+    std::fs::write(&test_env, "A=1\n").unwrap();
+    std::fs::write(&prod_env, "B=2\n").unwrap();
+
+    let mut config = Config::default();
+    config
+        .files
+        .protected_globs
+        .push("!**/.env.test".to_string());
+    let mut harness =
+        EditorTestHarness::with_config_and_working_dir(100, 24, config, project_root).unwrap();
+
+    harness.open_file(&test_env).unwrap();
+    harness.render().unwrap();
+    harness.assert_screen_not_contains("[protected]");
+    harness.type_text("x").unwrap();
+    assert_eq!(harness.get_buffer_content().unwrap(), "xA=1\n");
+
+    harness.open_file(&prod_env).unwrap();
+    harness.render().unwrap();
+    harness.assert_screen_contains("[protected]");
+}
@@ -1065,3 +1065,241 @@ fn test_recovery_insert_at_end_of_large_file() {
         println!("No chunked recovery entry found");
     }
 }
+
+/// Make the session lock in `recovery_dir` look like its editor crashed, by
+/// pointing it at a process that has exited
+fn simulate_crash(recovery_dir: &std::path::Path) {
+    use fresh::services::recovery::SessionInfo;
+
+    let mut child = std::process::Command::new(std::env::current_exe().unwrap())
+        .arg("--help")
+        .stdout(std::process::Stdio::null())
+        .spawn()
+        .unwrap();
+    let dead_pid = child.id();
+    child.wait().unwrap();
+
+    let info = SessionInfo {
+        pid: dead_pid,
+        ..SessionInfo::new()
+    };
+    std::fs::write(
+        recovery_dir.join("session.lock"),
+        serde_json::to_string(&info).unwrap(),
+    )
+    .unwrap();
+}
+
+/// Edit `file` in a session in `working_dir`, auto-save it for recovery, then
+/// "crash" (exit without ending the recovery session)
+fn crash_with_edit(
+    working_dir: &std::path::Path,
+    dir_context: &fresh::config_io::DirectoryContext,
+    file: &std::path::Path,
+    text: &str,
+) {
+    let mut config = fresh::config::Config::default();
+    config.editor.auto_save_interval_secs = 0;
+    let mut harness = EditorTestHarness::with_shared_dir_context(
+        80,
+        24,
+        config,
+        working_dir.to_path_buf(),
+        dir_context.clone(),
+    )
+    .unwrap();
+    harness.editor_mut().start_recovery_session().unwrap();
+
+    harness.open_file(file).unwrap();
+    harness.type_text(text).unwrap();
+    let saved = harness.editor_mut().auto_save_dirty_buffers().unwrap();
+    assert_eq!(saved, 1);
+
+    simulate_crash(harness.editor().recovery_dir());
+}
+
+/// Recovery after a crash only brings back buffers from the same project
+#[test]
+fn test_recovery_is_isolated_per_working_dir() {
+    use tempfile::TempDir;
+
+    let temp_dir = TempDir::new().unwrap();
+    let project_a = temp_dir.path().join("project_a");
+    let project_b = temp_dir.path().join("project_b");
+    std::fs::create_dir_all(&project_a).unwrap();
+    std::fs::create_dir_all(&project_b).unwrap();
+    let file_a = project_a.join("a.txt");
+    let file_b = project_b.join("b.txt");
+    std::fs::write(&file_a, "alpha").unwrap();
+    std::fs::write(&file_b, "beta").unwrap();
+
+    let context_temp = TempDir::new().unwrap();
+    let dir_context = fresh::config_io::DirectoryContext::for_testing(context_temp.path());
+
+    crash_with_edit(&project_a, &dir_context, &file_a, "A-EDIT ");
+    crash_with_edit(&project_b, &dir_context, &file_b, "B-EDIT ");
+
+    // Restart in project A: only A's buffer comes back
+    let mut harness = EditorTestHarness::with_shared_dir_context(
+        80,
+        24,
+        Default::default(),
+        project_a.clone(),
+        dir_context.clone(),
+    )
+    .unwrap();
+    assert!(harness.editor().has_recovery_files().unwrap());
+    let entries = harness.editor().list_recoverable_files().unwrap();
+    assert_eq!(entries.len(), 1);
+    assert_eq!(
+        entries[0].metadata.original_path.as_deref(),
+        Some(file_a.canonicalize().unwrap().as_path())
+    );
+
+    let recovered = harness.editor_mut().recover_all_buffers().unwrap();
+    assert_eq!(recovered, 1);
+    assert_eq!(harness.get_buffer_content().unwrap(), "A-EDIT alpha");
+    assert!(!harness.editor().has_recovery_files().unwrap());
+    drop(harness);
+
+    // Project B's recovery is untouched
+    let harness = EditorTestHarness::with_shared_dir_context(
+        80,
+        24,
+        Default::default(),
+        project_b.clone(),
+        dir_context.clone(),
+    )
+    .unwrap();
+    assert!(harness.editor().has_recovery_files().unwrap());
+    let entries = harness.editor().list_recoverable_files().unwrap();
+    assert_eq!(entries.len(), 1);
+    assert!(entries[0]
+        .metadata
+        .original_path
+        .as_ref()
+        .is_some_and(|p| p.ends_with("b.txt")));
+}
+
+/// An untitled buffer that was never saved comes back after a crash
+#[test]
+fn test_untitled_buffer_recovery_round_trip() {
+    use tempfile::TempDir;
+
+    let temp_dir = TempDir::new().unwrap();
+    let project = temp_dir.path().join("project");
+    std::fs::create_dir_all(&project).unwrap();
+    let context_temp = TempDir::new().unwrap();
+    let dir_context = fresh::config_io::DirectoryContext::for_testing(context_temp.path());
+
+    // Crash with notes typed into a new, never-saved buffer
+    {
+        let mut config = fresh::config::Config::default();
+        config.editor.auto_save_interval_secs = 0;
+        let mut harness = EditorTestHarness::with_shared_dir_context(
+            80,
+            24,
+            config,
+            project.clone(),
+            dir_context.clone(),
+        )
+        .unwrap();
+        harness.editor_mut().start_recovery_session().unwrap();
+
+        harness.editor_mut().new_buffer();
+        harness.type_text("scratch notes").unwrap();
+        assert_eq!(harness.editor_mut().auto_save_dirty_buffers().unwrap(), 1);
+
+        // Saving again reuses the same recovery entry
+        harness.type_text("!").unwrap();
+        assert_eq!(harness.editor_mut().auto_save_dirty_buffers().unwrap(), 1);
+        assert_eq!(harness.editor().list_recoverable_files().unwrap().len(), 1);
+
+        simulate_crash(harness.editor().recovery_dir());
+    }
+
+    let mut harness = EditorTestHarness::with_shared_dir_context(
+        80,
+        24,
+        Default::default(),
+        project.clone(),
+        dir_context.clone(),
+    )
+    .unwrap();
+    assert!(harness.editor().has_recovery_files().unwrap());
+    let entries = harness.editor().list_recoverable_files().unwrap();
+    assert_eq!(entries.len(), 1);
+    assert!(entries[0].metadata.original_path.is_none());
+    let name = entries[0].metadata.display_name();
+    assert!(name.starts_with("Untitled-"), "got {:?}", name);
+
+    assert_eq!(harness.editor_mut().recover_all_buffers().unwrap(), 1);
+    assert_eq!(harness.get_buffer_content().unwrap(), "scratch notes!");
+    assert!(harness.editor().active_state().buffer.is_modified());
+    assert!(harness.editor().active_state().buffer.file_path().is_none());
+    let active = harness.editor().active_buffer();
+    assert_eq!(harness.editor().get_buffer_display_name(active), name);
+}
+
+/// A recovery round only writes the buffers modified since the last one
+#[test]
+fn test_auto_save_writes_only_modified_buffers() {
+    use tempfile::TempDir;
+
+    let temp_dir = TempDir::new().unwrap();
+    let project = temp_dir.path().join("project");
+    std::fs::create_dir_all(&project).unwrap();
+    let context_temp = TempDir::new().unwrap();
+    let dir_context = fresh::config_io::DirectoryContext::for_testing(context_temp.path());
+
+    let mut config = fresh::config::Config::default();
+    config.editor.auto_save_interval_secs = 0;
+    let mut harness =
+        EditorTestHarness::with_shared_dir_context(80, 24, config, project.clone(), dir_context)
+            .unwrap();
+    harness.editor_mut().start_recovery_session().unwrap();
+
+    let paths: Vec<_> = (0..4)
+        .map(|i| {
+            let path = project.join(format!("file{i}.txt"));
+            std::fs::write(&path, format!("file {i}\n")).unwrap();
+            path
+        })
+        .collect();
+    for path in &paths {
+        harness.open_file(path).unwrap();
+        harness.type_text("edit ").unwrap();
+    }
+    assert_eq!(harness.editor_mut().auto_save_dirty_buffers().unwrap(), 4);
+    assert_eq!(harness.editor_mut().auto_save_dirty_buffers().unwrap(), 0);
+
+    // Only the buffer edited since is written again
+    harness.type_text("more").unwrap();
+    assert_eq!(harness.editor_mut().auto_save_dirty_buffers().unwrap(), 1);
+    assert_eq!(harness.editor().list_recoverable_files().unwrap().len(), 4);
+}
+
+/// Auto-save waits out the configured interval on the editor's clock
+#[test]
+fn test_auto_save_waits_for_interval() {
+    use std::time::Duration;
+
+    let mut config = fresh::config::Config::default();
+    config.editor.auto_save_interval_secs = 10;
+    let mut harness = EditorTestHarness::with_config(80, 24, config).unwrap();
+
+    harness.type_text("first").unwrap();
+    harness.advance_time(Duration::from_millis(9_999));
+    let saved = harness.editor_mut().auto_save_dirty_buffers().unwrap();
+    assert_eq!(saved, 0, "Should not auto-save before the interval");
+
+    harness.advance_time(Duration::from_millis(1));
+    let saved = harness.editor_mut().auto_save_dirty_buffers().unwrap();
+    assert_eq!(saved, 1, "Should auto-save once the interval has passed");
+
+    // The interval starts over from that save
+    harness.type_text(" second").unwrap();
+    harness.advance_time(Duration::from_secs(9));
+    let saved = harness.editor_mut().auto_save_dirty_buffers().unwrap();
+    assert_eq!(saved, 0, "Should not auto-save again within the interval");
+}
@@ -0,0 +1,307 @@
+//! Tests for editing files on other machines through a file provider
+
+use crate::common::harness::EditorTestHarness;
+use crossterm::event::{KeyCode, KeyModifiers};
+use fresh::services::file_loader::FileSource;
+use fresh::services::file_provider::FileProvider;
+use fresh::services::file_saver::FileSink;
+use fresh::services::fs::{FsEntry, FsEntryType};
+use std::collections::HashMap;
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// A host whose files live in memory, keyed by URL
+#[derive(Default)]
+struct MockHost {
+    files: Mutex<HashMap<String, Vec<u8>>>,
+    /// Saves fail like a dropped connection while set
+    fail_saves: AtomicBool,
+}
+
+impl MockHost {
+    fn with_files(files: &[(&str, &str)]) -> Arc<Self> {
+        let host = Self::default();
+        for (url, contents) in files {
+            host.files
+                .lock()
+                .unwrap()
+                .insert(url.to_string(), contents.as_bytes().to_vec());
+        }
+        Arc::new(host)
+    }
+
+    fn contents(&self, url: &str) -> Option<String> {
+        let files = self.files.lock().unwrap();
+        files
+            .get(url)
+            .map(|bytes| String::from_utf8_lossy(bytes).into_owned())
+    }
+
+    fn is_dir(&self, url: &str) -> bool {
+        let prefix = format!("{}/", url.trim_end_matches('/'));
+        self.files
+            .lock()
+            .unwrap()
+            .keys()
+            .any(|file| file.starts_with(&prefix))
+    }
+}
+
+fn key(path: &Path) -> String {
+    path.to_string_lossy().into_owned()
+}
+
+impl FileSource for MockHost {
+    fn size(&self, path: &Path) -> io::Result<u64> {
+        self.contents(&key(path))
+            .map(|contents| contents.len() as u64)
+            .ok_or_else(|| io::Error::from(io::ErrorKind::NotFound))
+    }
+
+    fn open(&self, path: &Path) -> io::Result<Box<dyn Read + Send>> {
+        let contents = self
+            .contents(&key(path))
+            .ok_or_else(|| io::Error::from(io::ErrorKind::NotFound))?;
+        Ok(Box::new(io::Cursor::new(contents.into_bytes())))
+    }
+}
+
+impl FileSink for MockHost {
+    fn replace(
+        &self,
+        path: &Path,
+        contents: &mut dyn FnMut(&mut dyn Write) -> io::Result<()>,
+    ) -> io::Result<u64> {
+        let mut bytes = Vec::new();
+        contents(&mut bytes)?;
+        if self.fail_saves.load(Ordering::SeqCst) {
+            return Err(io::Error::other("connection lost"));
+        }
+        let size = bytes.len() as u64;
+        self.files.lock().unwrap().insert(key(path), bytes);
+        Ok(size)
+    }
+}
+
+impl FileProvider for MockHost {
+    fn entry_type(&self, path: &Path) -> io::Result<Option<FsEntryType>> {
+        let url = key(path);
+        if self.contents(&url).is_some() {
+            Ok(Some(FsEntryType::File))
+        } else if self.is_dir(&url) {
+            Ok(Some(FsEntryType::Directory))
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<FsEntry>> {
+        let prefix = format!("{}/", key(path).trim_end_matches('/'));
+        let mut entries: HashMap<String, FsEntryType> = HashMap::new();
+        for file in self.files.lock().unwrap().keys() {
+            let Some(rest) = file.strip_prefix(&prefix) else {
+                continue;
+            };
+            match rest.split_once('/') {
+                Some((dir, _)) => entries.insert(dir.to_string(), FsEntryType::Directory),
+                None => entries.insert(rest.to_string(), FsEntryType::File),
+            };
+        }
+        Ok(entries
+            .into_iter()
+            .map(|(name, entry_type)| {
+                FsEntry::new(
+                    PathBuf::from(format!("{}{}", prefix, name)),
+                    name,
+                    entry_type,
+                )
+            })
+            .collect())
+    }
+}
+
+fn remote_harness(host: &Arc<MockHost>) -> EditorTestHarness {
+    let mut harness = EditorTestHarness::with_temp_project(100, 24).unwrap();
+    harness
+        .editor_mut()
+        .set_file_provider("ssh", Arc::clone(host) as Arc<dyn FileProvider>);
+    harness
+}
+
+fn save(harness: &mut EditorTestHarness) {
+    harness
+        .send_key(KeyCode::Char('s'), KeyModifiers::CONTROL)
+        .unwrap();
+}
+
+fn saved(harness: &EditorTestHarness) -> bool {
+    let buffer_id = harness.editor().active_buffer();
+    !harness.editor().is_buffer_saving(buffer_id)
+        && !harness.editor().active_state().buffer.is_modified()
+}
+
+#[test]
+fn test_edit_and_save_remote_file() {
+    let host = MockHost::with_files(&[("ssh://me@host/etc/hosts", "127.0.0.1 localhost\n")]);
+    let mut harness = remote_harness(&host);
+
+    harness
+        .open_file(Path::new("ssh://me@host//etc/./hosts"))
+        .unwrap();
+    harness
+        .wait_until(|h| h.get_buffer_content().as_deref() == Some("127.0.0.1 localhost\n"))
+        .unwrap();
+    // Buffers are keyed and shown by the normalized URL
+    assert_eq!(
+        harness.editor().active_state().buffer.file_path(),
+        Some(Path::new("ssh://me@host/etc/hosts"))
+    );
+    harness.assert_screen_contains("ssh://me@host/etc/hosts");
+
+    harness.type_text("# ").unwrap();
+    save(&mut harness);
+    harness.wait_until(saved).unwrap();
+    assert_eq!(
+        host.contents("ssh://me@host/etc/hosts").as_deref(),
+        Some("# 127.0.0.1 localhost\n")
+    );
+
+    // Opening it again switches to the open buffer
+    let buffer_id = harness.editor().active_buffer();
+    harness
+        .open_file(Path::new("ssh://me@host/etc/hosts"))
+        .unwrap();
+    assert_eq!(harness.editor().active_buffer(), buffer_id);
+}
+
+#[test]
+fn test_new_remote_file_is_created_on_save() {
+    let host = MockHost::with_files(&[("ssh://host/etc/hosts", "")]);
+    let mut harness = remote_harness(&host);
+
+    harness.open_file(Path::new("ssh://host/etc/motd")).unwrap();
+    assert_eq!(harness.get_buffer_content().as_deref(), Some(""));
+    harness.type_text("welcome").unwrap();
+    save(&mut harness);
+    harness.wait_until(saved).unwrap();
+    assert_eq!(
+        host.contents("ssh://host/etc/motd").as_deref(),
+        Some("welcome")
+    );
+}
+
+#[test]
+fn test_failed_remote_save_keeps_edits_and_offers_local_copy() {
+    let host = MockHost::with_files(&[("ssh://host/etc/hosts", "original\n")]);
+    let mut harness = remote_harness(&host);
+    harness
+        .open_file(Path::new("ssh://host/etc/hosts"))
+        .unwrap();
+    harness
+        .wait_until(|h| h.get_buffer_content().as_deref() == Some("original\n"))
+        .unwrap();
+
+    harness.type_text("edited ").unwrap();
+    host.fail_saves.store(true, Ordering::SeqCst);
+    save(&mut harness);
+    let buffer_id = harness.editor().active_buffer();
+    harness
+        .wait_until(|h| !h.editor().is_buffer_saving(buffer_id))
+        .unwrap();
+
+    // The remote file and the edits are untouched
+    assert_eq!(
+        host.contents("ssh://host/etc/hosts").as_deref(),
+        Some("original\n")
+    );
+    assert!(harness.editor().active_state().buffer.is_modified());
+    assert_eq!(
+        harness.editor().get_status_message().unwrap(),
+        "Failed to save hosts: connection lost"
+    );
+    harness.assert_screen_contains("Retry");
+    harness.assert_screen_contains("Save Local Copy");
+
+    // The second button writes the buffer to a local file
+    harness.editor_mut().focus_notifications();
+    harness.send_key(KeyCode::Tab, KeyModifiers::NONE).unwrap();
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    let message = harness.editor().get_status_message().unwrap().clone();
+    let local = message
+        .strip_prefix("Saved a local copy to ")
+        .unwrap_or_else(|| panic!("unexpected status: {}", message));
+    assert!(local.ends_with("ssh/host/etc/hosts"), "{}", local);
+    assert_eq!(std::fs::read_to_string(local).unwrap(), "edited original\n");
+    assert!(harness.editor().active_state().buffer.is_modified());
+
+    // Once the connection is back, saving writes the remote file
+    host.fail_saves.store(false, Ordering::SeqCst);
+    save(&mut harness);
+    harness.wait_until(saved).unwrap();
+    assert_eq!(
+        host.contents("ssh://host/etc/hosts").as_deref(),
+        Some("edited original\n")
+    );
+}
+
+#[test]
+fn test_remote_directory_listing() {
+    let host = MockHost::with_files(&[
+        ("ssh://host/etc/hosts", "127.0.0.1 localhost\n"),
+        ("ssh://host/etc/ssh/sshd_config", "Port 22\n"),
+    ]);
+    let mut harness = remote_harness(&host);
+
+    harness.open_file(Path::new("ssh://host/etc")).unwrap();
+    harness
+        .wait_until(|h| h.get_buffer_content().is_some_and(|c| c.contains("hosts")))
+        .unwrap();
+    let listing = harness.get_buffer_content().unwrap();
+    assert!(listing.contains("Remote directory: ssh://host/etc"));
+    // Parent first, then directories before files
+    let parent = listing.find("  ../").unwrap();
+    let dir = listing.find("  ssh/").unwrap();
+    let file = listing.find("  hosts").unwrap();
+    assert!(parent < dir && dir < file, "{}", listing);
+
+    // Enter on a directory lists it in the same buffer
+    let listing_buffer = harness.editor().active_buffer();
+    harness.send_key(KeyCode::Down, KeyModifiers::NONE).unwrap();
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    harness
+        .wait_until(|h| {
+            h.get_buffer_content()
+                .is_some_and(|c| c.contains("sshd_config"))
+        })
+        .unwrap();
+    assert_eq!(harness.editor().active_buffer(), listing_buffer);
+
+    // Enter on a file opens it
+    harness.send_key(KeyCode::Down, KeyModifiers::NONE).unwrap();
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    harness
+        .wait_until(|h| h.get_buffer_content().as_deref() == Some("Port 22\n"))
+        .unwrap();
+    assert_eq!(
+        harness.editor().active_state().buffer.file_path(),
+        Some(Path::new("ssh://host/etc/ssh/sshd_config"))
+    );
+}
+
+#[test]
+fn test_unknown_scheme_is_reported() {
+    let mut harness = EditorTestHarness::with_temp_project(80, 24).unwrap();
+    let error = harness
+        .editor_mut()
+        .open_file(Path::new("nosuch://host/file"))
+        .unwrap_err();
+    assert_eq!(error.to_string(), "Can't open 'nosuch://' locations");
+}
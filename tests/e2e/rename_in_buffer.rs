@@ -0,0 +1,137 @@
+use crate::common::harness::EditorTestHarness;
+use crossterm::event::{KeyCode, KeyModifiers};
+
+fn start_rename(harness: &mut EditorTestHarness) {
+    harness
+        .send_key(KeyCode::F(2), KeyModifiers::CONTROL)
+        .unwrap();
+}
+
+fn status(harness: &EditorTestHarness) -> String {
+    harness
+        .editor()
+        .get_status_message()
+        .cloned()
+        .unwrap_or_default()
+}
+
+/// With a selection only the occurrences inside it are renamed, and the
+/// rename is undone in one step
+#[test]
+fn test_rename_in_buffer_scoped_to_selection() {
+    let mut harness = EditorTestHarness::new(80, 24).unwrap();
+    let _fixture = harness
+        .load_buffer_from_text("a = 1\nb = a\na = 2\n")
+        .unwrap();
+
+    // Select the first two lines backwards, leaving the cursor on the first "a"
+    harness.send_key(KeyCode::Down, KeyModifiers::NONE).unwrap();
+    harness.send_key(KeyCode::End, KeyModifiers::NONE).unwrap();
+    harness.send_key(KeyCode::Up, KeyModifiers::SHIFT).unwrap();
+    harness
+        .send_key(KeyCode::Home, KeyModifiers::SHIFT)
+        .unwrap();
+
+    start_rename(&mut harness);
+    assert_eq!(harness.cursor_count(), 2);
+
+    harness.type_text("xy").unwrap();
+    harness.assert_buffer_content("xy = 1\nb = xy\na = 2\n");
+
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    harness.assert_buffer_content("xy = 1\nb = xy\na = 2\n");
+    assert_eq!(harness.cursor_count(), 1);
+
+    harness
+        .send_key(KeyCode::Char('z'), KeyModifiers::CONTROL)
+        .unwrap();
+    harness.assert_buffer_content("a = 1\nb = a\na = 2\n");
+}
+
+/// Occurrences in strings and comments are left out until toggled on
+#[test]
+fn test_rename_in_buffer_strings_and_comments_toggle() {
+    const SOURCE: &str =
+        "fn main() {\n    let name = 1;\n    // name here\n    println!(\"name {}\", name);\n}\n";
+    let mut harness = EditorTestHarness::with_temp_project(100, 30).unwrap();
+    let file = harness.project_dir().unwrap().join("main.rs");
+    std::fs::write(&file, SOURCE).unwrap();
+    harness.open_file(&file).unwrap();
+
+    // Cursor on the "name" of the let
+    harness.send_key(KeyCode::Down, KeyModifiers::NONE).unwrap();
+    harness
+        .send_key_repeat(KeyCode::Right, KeyModifiers::NONE, 8)
+        .unwrap();
+
+    start_rename(&mut harness);
+    assert_eq!(harness.cursor_count(), 2);
+    assert!(status(&harness).contains("strings/comments excluded"));
+
+    harness.type_text("count").unwrap();
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    harness.assert_buffer_content(
+        "fn main() {\n    let count = 1;\n    // name here\n    println!(\"name {}\", count);\n}\n",
+    );
+
+    harness
+        .send_key(KeyCode::Char('z'), KeyModifiers::CONTROL)
+        .unwrap();
+    harness.assert_buffer_content(SOURCE);
+
+    start_rename(&mut harness);
+    harness.type_text("count").unwrap();
+    harness
+        .send_key(KeyCode::Char('c'), KeyModifiers::ALT)
+        .unwrap();
+    assert_eq!(harness.cursor_count(), 4);
+    assert!(status(&harness).contains("strings/comments included"));
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    harness.assert_buffer_content(
+        "fn main() {\n    let count = 1;\n    // count here\n    println!(\"count {}\", count);\n}\n",
+    );
+}
+
+/// Escape puts back exactly the text and cursor from before the rename
+#[test]
+fn test_rename_in_buffer_abort_restores_content() {
+    const SOURCE: &str = "foo(foo, foo_bar);\nfoo\n";
+    let mut harness = EditorTestHarness::new(80, 24).unwrap();
+    let _fixture = harness.load_buffer_from_text(SOURCE).unwrap();
+    harness
+        .send_key_repeat(KeyCode::Right, KeyModifiers::NONE, 5)
+        .unwrap();
+
+    start_rename(&mut harness);
+    assert_eq!(harness.cursor_count(), 3);
+    harness.type_text("renamed").unwrap();
+    harness
+        .send_key(KeyCode::Backspace, KeyModifiers::NONE)
+        .unwrap();
+    harness.assert_buffer_content("rename(rename, foo_bar);\nrename\n");
+
+    harness.send_key(KeyCode::Esc, KeyModifiers::NONE).unwrap();
+    harness.assert_buffer_content(SOURCE);
+    assert_eq!(harness.cursor_count(), 1);
+    assert_eq!(harness.cursor_position(), 5);
+    assert!(!harness.editor().active_state().buffer.is_modified());
+
+    let state = harness.editor().active_state();
+    assert!(!state
+        .overlays
+        .all()
+        .iter()
+        .any(|o| o.namespace.as_ref().map(|n| n.as_str()) == Some("linked-rename")));
+
+    // Nothing was logged, so undo has nothing of the rename to revert
+    harness
+        .send_key(KeyCode::Char('z'), KeyModifiers::CONTROL)
+        .unwrap();
+    harness.assert_buffer_content(SOURCE);
+}
@@ -0,0 +1,158 @@
+//! Tests for ruler columns (`ui.rulers`) and overlength highlighting
+
+use crate::common::harness::EditorTestHarness;
+use crossterm::event::{KeyCode, KeyModifiers};
+use fresh::config::Config;
+use ratatui::style::Color;
+use tempfile::TempDir;
+
+fn find_on_screen(harness: &EditorTestHarness, text: &str) -> (u16, u16) {
+    harness
+        .screen_to_string()
+        .lines()
+        .enumerate()
+        .find_map(|(row, line)| {
+            line.find(text)
+                .map(|byte| (line[..byte].chars().count() as u16, row as u16))
+        })
+        .unwrap_or_else(|| panic!("{text:?} not on screen"))
+}
+
+fn bg(harness: &EditorTestHarness, x: u16, y: u16) -> Option<Color> {
+    harness.get_cell_style(x, y).unwrap().bg
+}
+
+/// Harness showing `content` from a file named `name`
+fn open(config: Config, name: &str, content: &str) -> (EditorTestHarness, TempDir) {
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join(name);
+    std::fs::write(&path, content).unwrap();
+    let mut harness = EditorTestHarness::with_config(80, 24, config).unwrap();
+    harness.open_file(&path).unwrap();
+    harness.render().unwrap();
+    (harness, temp_dir)
+}
+
+#[test]
+fn test_rulers_drawn_after_gutter() {
+    let mut config = Config::default();
+    config.ui.rulers = vec![10, 20];
+    // The cursor starts on the short line, keeping the highlight of the word
+    // under it off the long one
+    let (harness, _temp_dir) = open(
+        config,
+        "test.txt",
+        "short\n0123456789abcdefghijklmnopqrstuvwxyz\n",
+    );
+    let theme = harness.editor().theme();
+
+    let (text_x, y) = find_on_screen(&harness, "0123456789");
+    // Line numbers take the first columns
+    assert!(text_x > 0);
+
+    // The guide tints the column right after the 10th character
+    assert_eq!(harness.get_cell(text_x + 10, y).as_deref(), Some("a"));
+    assert_eq!(bg(&harness, text_x + 10, y), Some(theme.ruler_bg));
+    assert_eq!(bg(&harness, text_x + 9, y), Some(theme.editor_bg));
+    assert_eq!(bg(&harness, text_x + 11, y), Some(theme.editor_bg));
+    assert_eq!(harness.get_cell(text_x + 20, y).as_deref(), Some("k"));
+    assert_eq!(bg(&harness, text_x + 20, y), Some(theme.ruler_bg));
+
+    // Past the end of short lines and of the buffer too
+    let (_, last_row) = harness.content_area_rows();
+    for row in [y - 1, last_row as u16] {
+        assert_eq!(bg(&harness, text_x + 10, row), Some(theme.ruler_bg));
+        assert_eq!(bg(&harness, text_x + 20, row), Some(theme.ruler_bg));
+    }
+}
+
+#[test]
+fn test_rulers_follow_horizontal_scroll() {
+    let mut config = Config::default();
+    config.editor.line_wrap = false;
+    config.ui.rulers = vec![100];
+    // The cursor starts on a word of its own, keeping the highlight of the word
+    // under it off the ruler
+    let line = format!("a {}R{}\n", "x".repeat(98), "y".repeat(30));
+    let (mut harness, _temp_dir) = open(config, "test.txt", &line);
+    let theme = harness.editor().theme().clone();
+
+    // Scroll to the end of the line so column 100 moves left
+    harness.send_key(KeyCode::End, KeyModifiers::NONE).unwrap();
+    harness.render().unwrap();
+
+    let (x, y) = find_on_screen(&harness, "xRy");
+    let x = x + 1;
+    let (text_x, _) = find_on_screen(&harness, "xxxx");
+    assert!(x - text_x < 100, "line should be scrolled horizontally");
+    assert_eq!(bg(&harness, x, y), Some(theme.ruler_bg));
+    assert_eq!(bg(&harness, x - 1, y), Some(theme.editor_bg));
+    assert_eq!(bg(&harness, x + 1, y), Some(theme.editor_bg));
+}
+
+#[test]
+fn test_language_rulers_replace_global_ones() {
+    let mut config = Config::default();
+    config.ui.rulers = vec![10];
+    config.languages.get_mut("rust").unwrap().rulers = Some(vec![4]);
+    let (harness, _temp_dir) = open(config, "main.rs", "// 0123456789abcdef\n");
+    let theme = harness.editor().theme();
+
+    let (text_x, y) = find_on_screen(&harness, "// 0123");
+    assert_eq!(bg(&harness, text_x + 4, y), Some(theme.ruler_bg));
+    assert_eq!(bg(&harness, text_x + 10, y), Some(theme.editor_bg));
+}
+
+#[test]
+fn test_overlength_counts_tabs_and_wide_characters() {
+    let mut config = Config::default();
+    config.ui.rulers = vec![4, 8];
+    config.ui.highlight_overlength = true;
+    config.editor.tab_size = 4;
+    let (harness, _temp_dir) = open(config, "test.txt", "\tab\tcdefgh\n日本語abc\nok\n");
+    let theme = harness.editor().theme();
+
+    // The tab fills columns 0-3, so "c" after the second tab is column 8,
+    // the last ruler, where overlength starts
+    let (c_x, y) = find_on_screen(&harness, "cdefgh");
+    let text_x = c_x - 8;
+    assert_eq!(bg(&harness, c_x, y), Some(theme.overlength_bg));
+    assert_eq!(bg(&harness, c_x + 5, y), Some(theme.overlength_bg));
+    assert_eq!(bg(&harness, c_x - 1, y), Some(theme.editor_bg));
+    // Earlier rulers are still drawn
+    assert_eq!(bg(&harness, text_x + 4, y), Some(theme.ruler_bg));
+    // Past the end of the line only the ruler is drawn
+    assert_eq!(bg(&harness, c_x + 6, y), Some(theme.editor_bg));
+
+    // Wide characters take two columns: 語 starts at column 4, "a" at 6
+    let wide_y = y + 1;
+    assert_eq!(harness.get_cell(text_x + 4, wide_y).as_deref(), Some("語"));
+    assert_eq!(bg(&harness, text_x + 2, wide_y), Some(theme.editor_bg));
+    assert_eq!(harness.get_cell(text_x + 8, wide_y).as_deref(), Some("c"));
+    assert_eq!(bg(&harness, text_x + 8, wide_y), Some(theme.overlength_bg));
+    assert_eq!(bg(&harness, text_x + 7, wide_y), Some(theme.editor_bg));
+
+    // Short lines have nothing to tint
+    assert_eq!(bg(&harness, text_x + 1, y + 2), Some(theme.editor_bg));
+}
+
+#[test]
+fn test_selection_drawn_over_rulers_and_overlength() {
+    let mut config = Config::default();
+    config.ui.rulers = vec![4];
+    config.ui.highlight_overlength = true;
+    let (mut harness, _temp_dir) = open(config, "test.txt", "abcdefgh\nijklmnop\n");
+    let theme = harness.editor().theme().clone();
+
+    harness
+        .send_key(KeyCode::Down, KeyModifiers::SHIFT)
+        .unwrap();
+    harness.render().unwrap();
+
+    let (text_x, y) = find_on_screen(&harness, "abcdefgh");
+    // Selected text keeps the selection background past the ruler
+    assert_eq!(bg(&harness, text_x + 4, y), Some(theme.selection_bg));
+    assert_eq!(bg(&harness, text_x + 6, y), Some(theme.selection_bg));
+    // Unselected text past the ruler is tinted
+    assert_eq!(bg(&harness, text_x + 6, y + 1), Some(theme.overlength_bg));
+}
@@ -0,0 +1,227 @@
+//! E2E tests for per-language save actions
+//!
+//! Steps run in the configured order, their edits undo as one step, and
+//! `save_actions_on_failure` decides whether a failing step stops the save.
+
+use crate::common::harness::EditorTestHarness;
+use crossterm::event::{KeyCode, KeyModifiers};
+use fresh::config::{Config, FormatFailure, FormatterConfig, LanguageConfig, SaveActionFailure};
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+/// Write an executable formatter script to `dir`
+fn formatter_script(dir: &Path, name: &str, body: &str) -> PathBuf {
+    let path = dir.join(name);
+    std::fs::write(&path, format!("#!/bin/sh\n{}\n", body)).unwrap();
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+    }
+    path
+}
+
+/// A project with `file.txt` holding `content`, whose text files run
+/// `save_actions` on save
+fn save_actions_project(
+    temp_dir: &TempDir,
+    content: &str,
+    save_actions: &[&str],
+    on_failure: SaveActionFailure,
+    formatter: Option<&Path>,
+) -> (EditorTestHarness, PathBuf) {
+    let project_dir = temp_dir.path().join("project");
+    std::fs::create_dir(&project_dir).unwrap();
+    let file_path = project_dir.join("file.txt");
+    std::fs::write(&file_path, content).unwrap();
+
+    let mut config = Config::default();
+    config.languages.insert(
+        "plaintext".to_string(),
+        LanguageConfig {
+            extensions: vec!["txt".to_string()],
+            filenames: vec![],
+            grammar: "plaintext".to_string(),
+            comment_prefix: None,
+            auto_indent: false,
+            highlighter: Default::default(),
+            textmate_grammar: None,
+            show_whitespace_tabs: true,
+            use_tabs: false,
+            tab_size: None,
+            formatter: formatter.map(|script| FormatterConfig {
+                command: script.display().to_string(),
+                args: vec![],
+                stdin: true,
+                timeout_ms: 5000,
+                on_failure: FormatFailure::Save,
+            }),
+            format_on_save: false,
+            on_save: vec![],
+            indent_rules: None,
+            on_type_formatting: false,
+            rulers: None,
+            auto_complete: true,
+            completion_trigger_characters: vec![],
+            save_actions: save_actions.iter().map(|s| s.to_string()).collect(),
+            save_actions_on_failure: on_failure,
+            save_actions_timeout_ms: 5000,
+        },
+    );
+
+    let mut harness =
+        EditorTestHarness::with_config_and_working_dir(80, 24, config, project_dir).unwrap();
+    harness.open_file(&file_path).unwrap();
+    harness.render().unwrap();
+    (harness, file_path)
+}
+
+/// Wait until the active buffer's save actions finished and its save was written
+fn wait_for_save_actions(harness: &mut EditorTestHarness) {
+    let buffer_id = harness.editor().active_buffer();
+    harness
+        .wait_until(|h| {
+            !h.editor().is_running_save_actions(buffer_id)
+                && !h.editor().is_buffer_saving(buffer_id)
+        })
+        .unwrap();
+}
+
+/// Built-in steps and the formatter run in order before the save, and one
+/// undo takes all of their edits back
+#[test]
+#[cfg_attr(not(unix), ignore = "Save actions require Unix-like environment")]
+fn test_save_actions_run_in_order_and_undo_together() {
+    let temp_dir = TempDir::new().unwrap();
+    let script = formatter_script(temp_dir.path(), "upper.sh", "tr a-z A-Z");
+    let (mut harness, file_path) = save_actions_project(
+        &temp_dir,
+        "one  \ntwo\t",
+        &["trim_trailing_whitespace", "format", "ensure_final_newline"],
+        SaveActionFailure::Continue,
+        Some(&script),
+    );
+
+    harness
+        .send_key(KeyCode::Char('s'), KeyModifiers::CONTROL)
+        .unwrap();
+    wait_for_save_actions(&mut harness);
+
+    harness.assert_buffer_content("ONE\nTWO\n");
+    assert_eq!(std::fs::read_to_string(&file_path).unwrap(), "ONE\nTWO\n");
+    assert!(!harness.editor().active_state().buffer.is_modified());
+
+    harness
+        .send_key(KeyCode::Char('z'), KeyModifiers::CONTROL)
+        .unwrap();
+    harness.assert_buffer_content("one  \ntwo\t");
+}
+
+/// Each step works on the text the step before it left
+#[test]
+#[cfg_attr(not(unix), ignore = "Save actions require Unix-like environment")]
+fn test_save_actions_see_earlier_steps_edits() {
+    let temp_dir = TempDir::new().unwrap();
+    let script = formatter_script(temp_dir.path(), "append.sh", "cat; echo end");
+    let (mut harness, file_path) = save_actions_project(
+        &temp_dir,
+        "text",
+        &["ensure_final_newline", "format"],
+        SaveActionFailure::Continue,
+        Some(&script),
+    );
+
+    harness
+        .send_key(KeyCode::Char('s'), KeyModifiers::CONTROL)
+        .unwrap();
+    wait_for_save_actions(&mut harness);
+
+    assert_eq!(std::fs::read_to_string(&file_path).unwrap(), "text\nend\n");
+}
+
+/// By default a failing step is reported, and the remaining steps run and
+/// the file is saved
+#[test]
+#[cfg_attr(not(unix), ignore = "Save actions require Unix-like environment")]
+fn test_failing_save_action_continues_by_default() {
+    let temp_dir = TempDir::new().unwrap();
+    let (mut harness, file_path) = save_actions_project(
+        &temp_dir,
+        "text  ",
+        &[
+            "trim_trailing_whitespace",
+            "plugin:missing.step",
+            "ensure_final_newline",
+        ],
+        SaveActionFailure::Continue,
+        None,
+    );
+
+    harness
+        .send_key(KeyCode::Char('s'), KeyModifiers::CONTROL)
+        .unwrap();
+    wait_for_save_actions(&mut harness);
+
+    assert_eq!(std::fs::read_to_string(&file_path).unwrap(), "text\n");
+    let status = harness.editor().get_status_message().unwrap().clone();
+    assert!(
+        status.contains("plugin:missing.step") && status.ends_with("saved anyway"),
+        "{}",
+        status
+    );
+}
+
+/// With `save_actions_on_failure: "abort"`, a failing step stops the
+/// remaining steps and the file is left alone
+#[test]
+#[cfg_attr(not(unix), ignore = "Save actions require Unix-like environment")]
+fn test_failing_save_action_can_abort_the_save() {
+    let temp_dir = TempDir::new().unwrap();
+    let (mut harness, file_path) = save_actions_project(
+        &temp_dir,
+        "text  ",
+        &[
+            "trim_trailing_whitespace",
+            "plugin:missing.step",
+            "ensure_final_newline",
+        ],
+        SaveActionFailure::Abort,
+        None,
+    );
+
+    harness
+        .send_key(KeyCode::Char('s'), KeyModifiers::CONTROL)
+        .unwrap();
+    wait_for_save_actions(&mut harness);
+
+    harness.assert_buffer_content("text");
+    assert_eq!(std::fs::read_to_string(&file_path).unwrap(), "text  ");
+    assert!(harness.editor().active_state().buffer.is_modified());
+    let status = harness.editor().get_status_message().unwrap().clone();
+    assert!(
+        status.contains("plugin:missing.step") && status.ends_with("not saved"),
+        "{}",
+        status
+    );
+}
+
+/// Run Save Actions Without Saving edits the buffer but not the file
+#[test]
+#[cfg_attr(not(unix), ignore = "Save actions require Unix-like environment")]
+fn test_run_save_actions_without_saving() {
+    let temp_dir = TempDir::new().unwrap();
+    let (mut harness, file_path) = save_actions_project(
+        &temp_dir,
+        "text  ",
+        &["trim_trailing_whitespace", "ensure_final_newline"],
+        SaveActionFailure::Continue,
+        None,
+    );
+
+    harness.editor_mut().run_save_actions(false).unwrap();
+    wait_for_save_actions(&mut harness);
+
+    harness.assert_buffer_content("text\n");
+    assert!(harness.editor().active_state().buffer.is_modified());
+    assert_eq!(std::fs::read_to_string(&file_path).unwrap(), "text  ");
+}
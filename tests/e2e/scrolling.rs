@@ -821,6 +821,8 @@ fn test_viewport_31_rows() {
 
     // Suggestions popup should be visible with commands (alphabetically sorted, starting with A/C)
     harness.assert_screen_contains("Add Cursor");
+    harness.type_text("close").unwrap();
+    harness.render().unwrap();
     harness.assert_screen_contains("Close");
 
     // Note: With our viewport sync architecture, the viewport height may change
@@ -2414,3 +2416,179 @@ fn test_cursor_never_on_status_bar_when_scrolling() {
 
     println!("\n✓ All tests passed: cursor never jumped to status bar");
 }
+
+/// Run a command from the command palette
+fn run_palette_command(harness: &mut EditorTestHarness, name: &str) {
+    use crossterm::event::{KeyCode, KeyModifiers};
+    harness
+        .send_key(KeyCode::Char('p'), KeyModifiers::CONTROL)
+        .unwrap();
+    harness.type_text(name).unwrap();
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    harness.render().unwrap();
+}
+
+/// 100 lines of 8 bytes each ("Line 00\n" .. "Line 99"), cursor on `line`
+fn harness_on_line(line: usize) -> (EditorTestHarness, TestFixture) {
+    harness_with_config_on_line(fresh::config::Config::default(), line)
+}
+
+fn harness_with_config_on_line(
+    config: fresh::config::Config,
+    line: usize,
+) -> (EditorTestHarness, TestFixture) {
+    use crossterm::event::{KeyCode, KeyModifiers};
+    let mut harness = EditorTestHarness::with_config(80, 24, config).unwrap();
+    let content = (0..100)
+        .map(|i| format!("Line {i:02}"))
+        .collect::<Vec<_>>()
+        .join("\n");
+    let fixture = harness.load_buffer_from_text(&content).unwrap();
+    harness
+        .send_key(KeyCode::Home, KeyModifiers::CONTROL)
+        .unwrap();
+    for _ in 0..line {
+        harness.send_key(KeyCode::Down, KeyModifiers::NONE).unwrap();
+    }
+    harness.render().unwrap();
+    assert_eq!(harness.cursor_position(), line * 8);
+    (harness, fixture)
+}
+
+fn viewport_height(harness: &EditorTestHarness) -> usize {
+    harness.editor().active_viewport().visible_line_count()
+}
+
+/// The cursor line can be scrolled to the top, middle and bottom of the view
+/// without moving the cursor
+#[test]
+fn test_cursor_line_to_top_center_bottom() {
+    let (mut harness, _fixture) = harness_on_line(50);
+    let height = viewport_height(&harness);
+
+    run_palette_command(&mut harness, "Cursor Line to Top");
+    assert_eq!(harness.top_byte(), 50 * 8);
+    assert_eq!(harness.cursor_position(), 50 * 8);
+
+    run_palette_command(&mut harness, "Cursor Line to Bottom");
+    assert_eq!(harness.top_byte(), (50 - (height - 1)) * 8);
+    assert_eq!(harness.cursor_position(), 50 * 8);
+
+    run_palette_command(&mut harness, "Cursor Line to Center");
+    assert_eq!(harness.top_byte(), (50 - height / 2) * 8);
+    assert_eq!(harness.cursor_position(), 50 * 8);
+}
+
+/// Near the start and end of the buffer the view is clamped
+#[test]
+fn test_cursor_line_placement_clamps_at_buffer_edges() {
+    let (mut harness, _fixture) = harness_on_line(2);
+    let height = viewport_height(&harness);
+
+    run_palette_command(&mut harness, "Cursor Line to Bottom");
+    assert_eq!(harness.top_byte(), 0);
+    run_palette_command(&mut harness, "Cursor Line to Center");
+    assert_eq!(harness.top_byte(), 0);
+    run_palette_command(&mut harness, "Cursor Line to Top");
+    assert_eq!(harness.top_byte(), 2 * 8);
+    assert_eq!(harness.cursor_position(), 2 * 8);
+
+    // The last line can't go above the bottom of the view
+    let (mut harness, _fixture) = harness_on_line(99);
+    run_palette_command(&mut harness, "Cursor Line to Top");
+    assert_eq!(harness.top_byte(), (100 - height) * 8);
+    assert_eq!(harness.cursor_position(), 99 * 8);
+}
+
+/// Scrolling a line keeps the cursor where it is until it would leave the
+/// view, then pulls it to the nearest line inside the scroll offset
+#[test]
+fn test_scroll_keeping_cursor() {
+    use crossterm::event::{KeyCode, KeyModifiers};
+    // Bound to keys: opening the palette between steps would bring the
+    // cursor back inside the scroll offset before the next scroll
+    let mut config = fresh::config::Config::default();
+    for (key, action) in [
+        ("F4", "cursor_line_to_top"),
+        ("F6", "cursor_line_to_center"),
+        ("F7", "cursor_line_to_bottom"),
+        ("F8", "scroll_up_keep_cursor"),
+        ("F9", "scroll_down_keep_cursor"),
+    ] {
+        config.keybindings.push(fresh::config::Keybinding {
+            key: key.to_string(),
+            modifiers: vec![],
+            keys: vec![],
+            action: action.to_string(),
+            args: std::collections::HashMap::new(),
+            when: None,
+        });
+    }
+    let (mut harness, _fixture) = harness_with_config_on_line(config, 50);
+    let height = viewport_height(&harness);
+    let offset = harness.editor().active_viewport().scroll_offset;
+    let press = |harness: &mut EditorTestHarness, n: u8| {
+        harness.send_key(KeyCode::F(n), KeyModifiers::NONE).unwrap();
+    };
+
+    press(&mut harness, 6); // Center
+    press(&mut harness, 9); // Scroll down
+    assert_eq!(harness.top_byte(), (50 - height / 2 + 1) * 8);
+    assert_eq!(harness.cursor_position(), 50 * 8);
+
+    // With the cursor line at the bottom, scrolling up pulls it up
+    press(&mut harness, 7); // Bottom
+    let top_line = 50 - (height - 1);
+    press(&mut harness, 8); // Scroll up
+    assert_eq!(harness.top_byte(), (top_line - 1) * 8);
+    assert_eq!(
+        harness.cursor_position(),
+        (top_line - 1 + height - offset - 1) * 8
+    );
+
+    // With the cursor line at the top, scrolling down pulls it down
+    press(&mut harness, 4); // Top
+    let cursor_line = harness.cursor_position() / 8;
+    press(&mut harness, 9); // Scroll down
+    assert_eq!(harness.top_byte(), (cursor_line + 1) * 8);
+    assert_eq!(harness.cursor_position(), (cursor_line + 1 + offset) * 8);
+}
+
+/// At the top of the file there is nothing to scroll and the cursor stays
+#[test]
+fn test_scroll_keeping_cursor_at_top_of_file() {
+    let (mut harness, _fixture) = harness_on_line(0);
+
+    run_palette_command(&mut harness, "Scroll Up Keeping Cursor");
+    assert_eq!(harness.top_byte(), 0);
+    assert_eq!(harness.cursor_position(), 0);
+}
+
+/// Half-page scrolls move the view and the cursor together, so the cursor
+/// keeps its screen row
+#[test]
+fn test_scroll_half_page_keeps_screen_row() {
+    let (mut harness, _fixture) = harness_on_line(50);
+    let half = viewport_height(&harness) / 2;
+
+    run_palette_command(&mut harness, "Cursor Line to Center");
+    run_palette_command(&mut harness, "Scroll Half Page Down");
+    assert_eq!(harness.top_byte(), 50 * 8);
+    assert_eq!(harness.cursor_position(), (50 + half) * 8);
+
+    run_palette_command(&mut harness, "Scroll Half Page Up");
+    assert_eq!(harness.top_byte(), (50 - half) * 8);
+    assert_eq!(harness.cursor_position(), 50 * 8);
+}
+
+/// Near the top of the file a half-page scroll up moves only the cursor
+#[test]
+fn test_scroll_half_page_up_at_top_of_file() {
+    let (mut harness, _fixture) = harness_on_line(2);
+
+    run_palette_command(&mut harness, "Scroll Half Page Up");
+    assert_eq!(harness.top_byte(), 0);
+    assert_eq!(harness.cursor_position(), 0);
+}
@@ -1717,3 +1717,91 @@ fn test_find_selection_keeps_search_term() {
         "Fourth Ctrl+F3 should wrap around to first 'test'"
     );
 }
+
+/// Search for `query` with Ctrl+F, returning the screen before confirming
+fn smart_case_search(harness: &mut EditorTestHarness, query: &str, toggle: bool) -> String {
+    harness
+        .send_key(KeyCode::Char('f'), KeyModifiers::CONTROL)
+        .unwrap();
+    harness.type_text(query).unwrap();
+    if toggle {
+        harness
+            .send_key(KeyCode::Char('c'), KeyModifiers::ALT)
+            .unwrap();
+    }
+    harness.render().unwrap();
+    let screen = harness.screen_to_string();
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    harness.process_async_and_render().unwrap();
+    screen
+}
+
+/// With smart case, lowercase queries ignore case and queries with an
+/// uppercase letter match it; Alt+C flips the current search only
+#[test]
+fn test_smart_case_search() {
+    let temp_dir = TempDir::new().unwrap();
+    let file_path = temp_dir.path().join("test.txt");
+    std::fs::write(&file_path, "Hello hello HELLO").unwrap();
+
+    let mut config = fresh::config::Config::default();
+    config.search.smart_case = true;
+    let mut harness = EditorTestHarness::with_config(80, 24, config).unwrap();
+    harness.open_file(&file_path).unwrap();
+    harness.render().unwrap();
+
+    let screen = smart_case_search(&mut harness, "hello", false);
+    assert!(screen.contains("[aa] Smart Case"), "{}", screen);
+    assert_eq!(harness.cursor_position(), 0, "'hello' should find 'Hello'");
+
+    let screen = smart_case_search(&mut harness, "HELLO", false);
+    assert!(screen.contains("[Aa] Smart Case"), "{}", screen);
+    assert_eq!(
+        harness.cursor_position(),
+        12,
+        "'HELLO' should find only 'HELLO'"
+    );
+
+    // Alt+C makes this search match case, wrapping around to "hello"
+    let screen = smart_case_search(&mut harness, "hello", true);
+    assert!(screen.contains("[Aa] Smart Case"), "{}", screen);
+    assert_eq!(harness.cursor_position(), 6);
+    assert!(harness.editor().config().search.smart_case);
+
+    // The next search goes by its query again
+    let screen = smart_case_search(&mut harness, "hello", false);
+    assert!(screen.contains("[aa] Smart Case"), "{}", screen);
+}
+
+/// Replace with preserve case follows the case of each match
+#[test]
+fn test_replace_preserves_case() {
+    let temp_dir = TempDir::new().unwrap();
+    let file_path = temp_dir.path().join("test.txt");
+    std::fs::write(&file_path, "foo Foo FOO fOo").unwrap();
+
+    let mut config = fresh::config::Config::default();
+    config.search.smart_case = true;
+    config.search.preserve_case = true;
+    let mut harness = EditorTestHarness::with_config(80, 24, config).unwrap();
+    harness.open_file(&file_path).unwrap();
+    harness.render().unwrap();
+
+    harness
+        .send_key(KeyCode::Char('r'), KeyModifiers::CONTROL)
+        .unwrap();
+    harness.type_text("foo").unwrap();
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    harness.type_text("bar").unwrap();
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    harness.render().unwrap();
+
+    assert_eq!(harness.get_buffer_content().unwrap(), "bar Bar BAR bar");
+    harness.assert_screen_contains("Replaced 4 occ");
+}
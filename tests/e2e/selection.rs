@@ -1579,3 +1579,195 @@ fn test_select_at_file_boundaries() {
     // Just verify we can get selected text without panicking
     // The test validates that boundary operations don't crash
 }
+
+/// Python-like fixture with nested indentation and a blank line inside a block
+const NESTED_BLOCKS: &str = "class Shape:
+    def area(self):
+        width = self.width
+        height = self.height
+
+        return width * height
+
+    def name(self):
+        return \"shape\"
+";
+
+/// An editor with the structural selection actions on function keys
+fn structural_selection_harness() -> EditorTestHarness {
+    let mut config = fresh::config::Config::default();
+    for (key, action) in [
+        ("F5", "expand_selection"),
+        ("F6", "shrink_selection"),
+        ("F7", "select_paragraph"),
+        ("F8", "expand_selection_to_indentation"),
+    ] {
+        config.keybindings.push(fresh::config::Keybinding {
+            key: key.to_string(),
+            modifiers: vec![],
+            keys: vec![],
+            action: action.to_string(),
+            args: std::collections::HashMap::new(),
+            when: None,
+        });
+    }
+    EditorTestHarness::with_config(80, 24, config).unwrap()
+}
+
+fn place_cursor(harness: &mut EditorTestHarness, position: usize) {
+    let cursor = harness
+        .editor_mut()
+        .active_state_mut()
+        .cursors
+        .primary_mut();
+    cursor.clear_selection();
+    cursor.position = position;
+}
+
+/// Selected texts of all cursors, in buffer order
+fn selected_texts(harness: &mut EditorTestHarness) -> Vec<String> {
+    let state = harness.editor_mut().active_state_mut();
+    let mut ranges = state.cursors.selections();
+    ranges.sort_by_key(|r| r.start);
+    ranges
+        .into_iter()
+        .map(|r| state.get_text_range(r.start, r.end))
+        .collect()
+}
+
+/// Expanding steps word → line → indentation block → buffer, and shrinking
+/// retraces the same steps back to the bare cursor
+#[test]
+fn test_expand_and_shrink_selection_ladder() {
+    let mut harness = structural_selection_harness();
+    let _fixture = harness.load_buffer_from_text(NESTED_BLOCKS).unwrap();
+    let start = NESTED_BLOCKS.find("return width").unwrap() + 2;
+    place_cursor(&mut harness, start);
+
+    let block = "        width = self.width\n        height = self.height\n\n        return width * height\n";
+    let ladder = [
+        "return",
+        "        return width * height\n",
+        block,
+        NESTED_BLOCKS,
+    ];
+    for expected in ladder {
+        harness.send_key(KeyCode::F(5), KeyModifiers::NONE).unwrap();
+        assert_eq!(harness.get_selected_text(), expected);
+    }
+    // Nothing left to grow into
+    harness.send_key(KeyCode::F(5), KeyModifiers::NONE).unwrap();
+    assert_eq!(harness.get_selected_text(), NESTED_BLOCKS);
+
+    for expected in ladder.iter().rev().skip(1) {
+        harness.send_key(KeyCode::F(6), KeyModifiers::NONE).unwrap();
+        assert_eq!(harness.get_selected_text(), *expected);
+    }
+    harness.send_key(KeyCode::F(6), KeyModifiers::NONE).unwrap();
+    harness.assert_no_selection();
+    assert_eq!(harness.cursor_position(), start);
+}
+
+/// Shrinking does nothing once the selection has been changed another way
+#[test]
+fn test_shrink_selection_forgets_moved_selection() {
+    let mut harness = structural_selection_harness();
+    let _fixture = harness.load_buffer_from_text(NESTED_BLOCKS).unwrap();
+    place_cursor(&mut harness, NESTED_BLOCKS.find("height =").unwrap());
+
+    harness.send_key(KeyCode::F(5), KeyModifiers::NONE).unwrap();
+    harness
+        .send_key(KeyCode::Right, KeyModifiers::SHIFT)
+        .unwrap();
+    let selected = harness.get_selected_text();
+    harness.send_key(KeyCode::F(6), KeyModifiers::NONE).unwrap();
+    assert_eq!(harness.get_selected_text(), selected);
+}
+
+/// The indentation block keeps blank lines inside it, also when started on one
+#[test]
+fn test_expand_selection_to_indentation_with_blank_lines() {
+    let mut harness = structural_selection_harness();
+    let _fixture = harness.load_buffer_from_text(NESTED_BLOCKS).unwrap();
+    let block = "        width = self.width\n        height = self.height\n\n        return width * height\n";
+
+    place_cursor(&mut harness, NESTED_BLOCKS.find("width =").unwrap());
+    harness.send_key(KeyCode::F(8), KeyModifiers::NONE).unwrap();
+    assert_eq!(harness.get_selected_text(), block);
+
+    // On the blank line inside the block, the indent of the line below is used
+    place_cursor(&mut harness, NESTED_BLOCKS.find("\n\n").unwrap() + 1);
+    harness.send_key(KeyCode::F(8), KeyModifiers::NONE).unwrap();
+    assert_eq!(harness.get_selected_text(), block);
+
+    // A method's block includes both methods at that depth and their bodies
+    place_cursor(&mut harness, NESTED_BLOCKS.find("def name").unwrap());
+    harness.send_key(KeyCode::F(8), KeyModifiers::NONE).unwrap();
+    assert_eq!(
+        harness.get_selected_text(),
+        &NESTED_BLOCKS[NESTED_BLOCKS.find("    def area").unwrap()..]
+    );
+}
+
+/// A paragraph is the run of non-blank lines; a blank line has none
+#[test]
+fn test_select_paragraph() {
+    let mut harness = structural_selection_harness();
+    let _fixture = harness.load_buffer_from_text(NESTED_BLOCKS).unwrap();
+
+    place_cursor(&mut harness, NESTED_BLOCKS.find("width =").unwrap());
+    harness.send_key(KeyCode::F(7), KeyModifiers::NONE).unwrap();
+    assert_eq!(
+        harness.get_selected_text(),
+        "class Shape:\n    def area(self):\n        width = self.width\n        height = self.height\n"
+    );
+
+    place_cursor(&mut harness, NESTED_BLOCKS.find("\n\n").unwrap() + 1);
+    harness.send_key(KeyCode::F(7), KeyModifiers::NONE).unwrap();
+    harness.assert_no_selection();
+}
+
+/// Repeating select line extends the selection downward
+#[test]
+fn test_select_line_repeat_extends_down() {
+    let mut harness = structural_selection_harness();
+    let _fixture = harness.load_buffer_from_text(NESTED_BLOCKS).unwrap();
+    place_cursor(&mut harness, NESTED_BLOCKS.find("width =").unwrap());
+
+    harness
+        .send_key(KeyCode::Char('l'), KeyModifiers::CONTROL)
+        .unwrap();
+    assert_eq!(harness.get_selected_text(), "        width = self.width\n");
+    harness
+        .send_key(KeyCode::Char('l'), KeyModifiers::CONTROL)
+        .unwrap();
+    assert_eq!(
+        harness.get_selected_text(),
+        "        width = self.width\n        height = self.height\n"
+    );
+}
+
+/// Every cursor expands and shrinks through its own ladder
+#[test]
+fn test_expand_selection_multi_cursor() {
+    let mut harness = structural_selection_harness();
+    let _fixture = harness.load_buffer_from_text(NESTED_BLOCKS).unwrap();
+    place_cursor(&mut harness, NESTED_BLOCKS.find("width =").unwrap());
+    harness
+        .editor_mut()
+        .active_state_mut()
+        .cursors
+        .add(fresh::model::cursor::Cursor::new(
+            NESTED_BLOCKS.find("return \"shape\"").unwrap(),
+        ));
+
+    harness.send_key(KeyCode::F(5), KeyModifiers::NONE).unwrap();
+    assert_eq!(selected_texts(&mut harness), vec!["width", "return"]);
+    harness.send_key(KeyCode::F(5), KeyModifiers::NONE).unwrap();
+    assert_eq!(
+        selected_texts(&mut harness),
+        vec!["        width = self.width\n", "        return \"shape\"\n"]
+    );
+
+    harness.send_key(KeyCode::F(6), KeyModifiers::NONE).unwrap();
+    assert_eq!(selected_texts(&mut harness), vec!["width", "return"]);
+}
@@ -0,0 +1,89 @@
+//! E2E tests for the selection counts in the status bar and "Selection
+//! Statistics"
+
+use crate::common::harness::EditorTestHarness;
+use crossterm::event::{KeyCode, KeyModifiers};
+use fresh::services::plugins::api::PluginCommand;
+
+fn execute_action(harness: &mut EditorTestHarness, name: &str) {
+    harness
+        .editor_mut()
+        .handle_plugin_command(PluginCommand::ExecuteAction {
+            action_name: name.to_string(),
+        })
+        .unwrap();
+    harness.render().unwrap();
+}
+
+/// The status bar counts code points without reading the text; the command
+/// counts what the user sees as characters
+#[test]
+fn test_selection_counts_emoji_and_cjk() {
+    let mut harness = EditorTestHarness::new(120, 30).unwrap();
+    // The family emoji is one grapheme of five code points and 18 bytes
+    let _fixture = harness.load_buffer_from_text("héllo 👨‍👩‍👧\n日本語\n").unwrap();
+    harness.render().unwrap();
+    harness.assert_screen_not_contains("sel:");
+
+    execute_action(&mut harness, "select_all");
+    harness.assert_screen_contains("sel: 2 lines, 16 chars");
+
+    execute_action(&mut harness, "selection_statistics");
+    harness.assert_screen_contains("Selection Statistics");
+    harness.assert_screen_contains("Lines: 2");
+    harness.assert_screen_contains("Words: 4");
+    harness.assert_screen_contains("Characters: 12");
+    harness.assert_screen_contains("Bytes: 36");
+    harness.assert_screen_not_contains("By region:");
+}
+
+/// With several cursors the segment shows the totals next to the cursor
+/// count, and the command lists each region
+#[test]
+fn test_selection_counts_total_multiple_cursors() {
+    let mut harness = EditorTestHarness::new(120, 30).unwrap();
+    let _fixture = harness
+        .load_buffer_from_text("foo bar\nfoo baz\nfoo\n")
+        .unwrap();
+    harness.render().unwrap();
+
+    for _ in 0..3 {
+        harness
+            .send_key(KeyCode::Right, KeyModifiers::SHIFT)
+            .unwrap();
+    }
+    execute_action(&mut harness, "add_cursor_next_match");
+    execute_action(&mut harness, "add_cursor_next_match");
+    harness.assert_screen_contains("3 cursors | sel: 3 lines, 9 chars");
+
+    execute_action(&mut harness, "selection_statistics");
+    harness.assert_screen_contains("Regions: 3");
+    harness.assert_screen_contains("Words: 3");
+    harness.assert_screen_contains("By region:");
+    harness.assert_screen_contains("2. Ln 2, Col 1: 1 words, 3 chars, 3 bytes");
+}
+
+/// The segment goes away once the selection collapses
+#[test]
+fn test_selection_segment_disappears_when_selection_collapses() {
+    let mut harness = EditorTestHarness::new(120, 30).unwrap();
+    let _fixture = harness.load_buffer_from_text("hello world\n").unwrap();
+    harness.render().unwrap();
+
+    for _ in 0..5 {
+        harness
+            .send_key(KeyCode::Right, KeyModifiers::SHIFT)
+            .unwrap();
+    }
+    harness.render().unwrap();
+    harness.assert_screen_contains("sel: 1 lines, 5 chars");
+
+    harness
+        .send_key(KeyCode::Right, KeyModifiers::NONE)
+        .unwrap();
+    harness.render().unwrap();
+    harness.assert_screen_not_contains("sel:");
+
+    execute_action(&mut harness, "selection_statistics");
+    harness.assert_screen_contains("Nothing is selected");
+}
@@ -6,6 +6,7 @@
 use crate::common::harness::EditorTestHarness;
 use crossterm::event::{KeyCode, KeyModifiers};
 use fresh::config::Config;
+use fresh::config_io::DirectoryContext;
 use fresh::session::get_session_path;
 use tempfile::TempDir;
 
@@ -13,6 +14,7 @@ use tempfile::TempDir;
 #[test]
 fn test_session_saves_and_restores_open_files() {
     let temp_dir = TempDir::new().unwrap();
+    let dir_context = DirectoryContext::for_testing(&temp_dir.path().join("dirs"));
     let project_dir = temp_dir.path().join("project");
     std::fs::create_dir(&project_dir).unwrap();
 
@@ -23,11 +25,12 @@ fn test_session_saves_and_restores_open_files() {
 
     // First session: open files and save
     {
-        let mut harness = EditorTestHarness::with_config_and_working_dir(
+        let mut harness = EditorTestHarness::with_shared_dir_context(
             80,
             24,
             Config::default(),
             project_dir.clone(),
+            dir_context.clone(),
         )
         .unwrap();
 
@@ -42,11 +45,12 @@ fn test_session_saves_and_restores_open_files() {
 
     // Second session: restore and verify
     {
-        let mut harness = EditorTestHarness::with_config_and_working_dir(
+        let mut harness = EditorTestHarness::with_shared_dir_context(
             80,
             24,
             Config::default(),
             project_dir.clone(),
+            dir_context.clone(),
         )
         .unwrap();
 
@@ -70,6 +74,7 @@ fn test_session_saves_and_restores_open_files() {
 #[test]
 fn test_session_restores_cursor_line() {
     let temp_dir = TempDir::new().unwrap();
+    let dir_context = DirectoryContext::for_testing(&temp_dir.path().join("dirs"));
     let project_dir = temp_dir.path().join("project");
     std::fs::create_dir(&project_dir).unwrap();
 
@@ -82,11 +87,12 @@ fn test_session_restores_cursor_line() {
 
     // First session: move cursor to line 5
     {
-        let mut harness = EditorTestHarness::with_config_and_working_dir(
+        let mut harness = EditorTestHarness::with_shared_dir_context(
             80,
             24,
             Config::default(),
             project_dir.clone(),
+            dir_context.clone(),
         )
         .unwrap();
 
@@ -107,11 +113,12 @@ fn test_session_restores_cursor_line() {
 
     // Second session: restore and verify cursor position is restored
     {
-        let mut harness = EditorTestHarness::with_config_and_working_dir(
+        let mut harness = EditorTestHarness::with_shared_dir_context(
             80,
             24,
             Config::default(),
             project_dir.clone(),
+            dir_context.clone(),
         )
         .unwrap();
 
@@ -131,6 +138,7 @@ fn test_session_restores_cursor_line() {
 #[test]
 fn test_session_handles_missing_files() {
     let temp_dir = TempDir::new().unwrap();
+    let dir_context = DirectoryContext::for_testing(&temp_dir.path().join("dirs"));
     let project_dir = temp_dir.path().join("project");
     std::fs::create_dir(&project_dir).unwrap();
 
@@ -141,11 +149,12 @@ fn test_session_handles_missing_files() {
 
     // First session: open both files
     {
-        let mut harness = EditorTestHarness::with_config_and_working_dir(
+        let mut harness = EditorTestHarness::with_shared_dir_context(
             80,
             24,
             Config::default(),
             project_dir.clone(),
+            dir_context.clone(),
         )
         .unwrap();
 
@@ -165,11 +174,12 @@ fn test_session_handles_missing_files() {
 
     // Second session: should restore without error
     {
-        let mut harness = EditorTestHarness::with_config_and_working_dir(
+        let mut harness = EditorTestHarness::with_shared_dir_context(
             80,
             24,
             Config::default(),
             project_dir.clone(),
+            dir_context.clone(),
         )
         .unwrap();
 
@@ -190,6 +200,7 @@ fn test_session_handles_missing_files() {
 #[test]
 fn test_no_session_flag_behavior() {
     let temp_dir = TempDir::new().unwrap();
+    let dir_context = DirectoryContext::for_testing(&temp_dir.path().join("dirs"));
     let project_dir = temp_dir.path().join("project");
     std::fs::create_dir(&project_dir).unwrap();
 
@@ -198,11 +209,12 @@ fn test_no_session_flag_behavior() {
 
     // First: save a session with the file
     {
-        let mut harness = EditorTestHarness::with_config_and_working_dir(
+        let mut harness = EditorTestHarness::with_shared_dir_context(
             80,
             24,
             Config::default(),
             project_dir.clone(),
+            dir_context.clone(),
         )
         .unwrap();
 
@@ -216,11 +228,12 @@ fn test_no_session_flag_behavior() {
     // Second: create new editor WITHOUT restoring
     // This simulates --no-session flag behavior
     {
-        let mut harness = EditorTestHarness::with_config_and_working_dir(
+        let mut harness = EditorTestHarness::with_shared_dir_context(
             80,
             24,
             Config::default(),
             project_dir.clone(),
+            dir_context.clone(),
         )
         .unwrap();
 
@@ -237,6 +250,7 @@ fn test_no_session_flag_behavior() {
 #[test]
 fn test_session_restores_multiple_files() {
     let temp_dir = TempDir::new().unwrap();
+    let dir_context = DirectoryContext::for_testing(&temp_dir.path().join("dirs"));
     let project_dir = temp_dir.path().join("project");
     std::fs::create_dir(&project_dir).unwrap();
 
@@ -251,11 +265,12 @@ fn test_session_restores_multiple_files() {
 
     // First session: open all files
     {
-        let mut harness = EditorTestHarness::with_config_and_working_dir(
+        let mut harness = EditorTestHarness::with_shared_dir_context(
             80,
             24,
             Config::default(),
             project_dir.clone(),
+            dir_context.clone(),
         )
         .unwrap();
 
@@ -271,11 +286,12 @@ fn test_session_restores_multiple_files() {
 
     // Second session: verify all restored
     {
-        let mut harness = EditorTestHarness::with_config_and_working_dir(
+        let mut harness = EditorTestHarness::with_shared_dir_context(
             80,
             24,
             Config::default(),
             project_dir.clone(),
+            dir_context.clone(),
         )
         .unwrap();
 
@@ -289,26 +305,36 @@ fn test_session_restores_multiple_files() {
     }
 }
 
-/// Test that session file is created in the correct XDG location
+/// Test that session file is created in the state directory
 #[test]
 fn test_session_file_location() {
     let temp_dir = TempDir::new().unwrap();
     let project_dir = temp_dir.path().join("my_project");
     std::fs::create_dir(&project_dir).unwrap();
+    let dir_context = DirectoryContext::for_testing(&temp_dir.path().join("dirs"));
 
-    // Get expected session path
-    let session_path = get_session_path(&project_dir).unwrap();
+    let mut harness = EditorTestHarness::with_shared_dir_context(
+        80,
+        24,
+        Config::default(),
+        project_dir.clone(),
+        dir_context.clone(),
+    )
+    .unwrap();
+    harness.editor_mut().save_session().unwrap();
 
-    // Verify XDG location
-    let data_dir = dirs::data_dir().unwrap();
+    // Get expected session path
+    let session_path = get_session_path(&dir_context.sessions_dir(), &project_dir);
     assert!(
-        session_path.starts_with(&data_dir),
-        "Session should be in XDG data directory: {:?}",
+        session_path.exists(),
+        "Session should be saved to {:?}",
         session_path
     );
+
+    // Verify state directory location
     assert!(
-        session_path.to_string_lossy().contains("fresh"),
-        "Session should be in 'fresh' subdirectory: {:?}",
+        session_path.starts_with(&dir_context.state_dir),
+        "Session should be in the state directory: {:?}",
         session_path
     );
     assert!(
@@ -335,6 +361,58 @@ fn test_session_file_location() {
     );
 }
 
+/// Test that a session left in the legacy data directory is restored and,
+/// once the user accepts the popup, moved to the state directory
+#[test]
+fn test_legacy_session_is_migrated() {
+    let temp_dir = TempDir::new().unwrap();
+    let project_dir = temp_dir.path().join("project");
+    std::fs::create_dir(&project_dir).unwrap();
+    let file = project_dir.join("old.txt");
+    std::fs::write(&file, "from before the move").unwrap();
+
+    // An older version wrote its session to what is now the legacy directory
+    let old_dirs = DirectoryContext::for_testing(&temp_dir.path().join("old"));
+    {
+        let mut harness = EditorTestHarness::with_shared_dir_context(
+            80,
+            24,
+            Config::default(),
+            project_dir.clone(),
+            old_dirs.clone(),
+        )
+        .unwrap();
+        harness.open_file(&file).unwrap();
+        harness.editor_mut().save_session().unwrap();
+    }
+
+    let mut dir_context = DirectoryContext::for_testing(&temp_dir.path().join("new"));
+    dir_context.legacy_state_dir = Some(old_dirs.state_dir.clone());
+    let mut harness = EditorTestHarness::with_shared_dir_context(
+        80,
+        24,
+        Config::default(),
+        project_dir.clone(),
+        dir_context.clone(),
+    )
+    .unwrap();
+    assert!(harness.editor_mut().try_restore_session().unwrap());
+    harness.render().unwrap();
+    harness.assert_screen_contains("old.txt");
+
+    harness.editor_mut().offer_state_migration();
+    harness.render().unwrap();
+    harness.assert_screen_contains("Move");
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+
+    assert!(get_session_path(&dir_context.sessions_dir(), &project_dir).exists());
+    assert!(!get_session_path(&old_dirs.sessions_dir(), &project_dir).exists());
+    // Moving is offered only once
+    assert!(dir_context.pending_state_migration().is_none());
+}
+
 /// Test session roundtrip: capture and verify data integrity
 #[test]
 fn test_session_data_integrity() {
@@ -384,6 +462,7 @@ fn test_session_data_integrity() {
 #[test]
 fn test_session_restores_scroll_position() {
     let temp_dir = TempDir::new().unwrap();
+    let dir_context = DirectoryContext::for_testing(&temp_dir.path().join("dirs"));
     let project_dir = temp_dir.path().join("project");
     std::fs::create_dir(&project_dir).unwrap();
 
@@ -396,11 +475,12 @@ fn test_session_restores_scroll_position() {
 
     // First session: scroll down significantly
     {
-        let mut harness = EditorTestHarness::with_config_and_working_dir(
+        let mut harness = EditorTestHarness::with_shared_dir_context(
             80,
             24,
             Config::default(),
             project_dir.clone(),
+            dir_context.clone(),
         )
         .unwrap();
 
@@ -423,11 +503,12 @@ fn test_session_restores_scroll_position() {
 
     // Second session: verify scroll position restored
     {
-        let mut harness = EditorTestHarness::with_config_and_working_dir(
+        let mut harness = EditorTestHarness::with_shared_dir_context(
             80,
             24,
             Config::default(),
             project_dir.clone(),
+            dir_context.clone(),
         )
         .unwrap();
 
@@ -443,6 +524,7 @@ fn test_session_restores_scroll_position() {
 #[test]
 fn test_session_preserves_active_tab() {
     let temp_dir = TempDir::new().unwrap();
+    let dir_context = DirectoryContext::for_testing(&temp_dir.path().join("dirs"));
     let project_dir = temp_dir.path().join("project");
     std::fs::create_dir(&project_dir).unwrap();
 
@@ -453,11 +535,12 @@ fn test_session_preserves_active_tab() {
 
     // First session: open both files, switch to first
     {
-        let mut harness = EditorTestHarness::with_config_and_working_dir(
+        let mut harness = EditorTestHarness::with_shared_dir_context(
             80,
             24,
             Config::default(),
             project_dir.clone(),
+            dir_context.clone(),
         )
         .unwrap();
 
@@ -477,11 +560,12 @@ fn test_session_preserves_active_tab() {
 
     // Second session: should restore with first file active
     {
-        let mut harness = EditorTestHarness::with_config_and_working_dir(
+        let mut harness = EditorTestHarness::with_shared_dir_context(
             80,
             24,
             Config::default(),
             project_dir.clone(),
+            dir_context.clone(),
         )
         .unwrap();
 
@@ -522,6 +606,7 @@ fn prev_split(harness: &mut EditorTestHarness) {
 #[test]
 fn test_session_restores_cursor_in_splits() {
     let temp_dir = TempDir::new().unwrap();
+    let dir_context = DirectoryContext::for_testing(&temp_dir.path().join("dirs"));
     let project_dir = temp_dir.path().join("project");
     std::fs::create_dir(&project_dir).unwrap();
 
@@ -538,11 +623,12 @@ fn test_session_restores_cursor_in_splits() {
 
     // First session: create splits and move cursors
     {
-        let mut harness = EditorTestHarness::with_config_and_working_dir(
+        let mut harness = EditorTestHarness::with_shared_dir_context(
             80,
             24,
             Config::default(),
             project_dir.clone(),
+            dir_context.clone(),
         )
         .unwrap();
 
@@ -572,11 +658,12 @@ fn test_session_restores_cursor_in_splits() {
 
     // Second session: restore and verify cursor positions
     {
-        let mut harness = EditorTestHarness::with_config_and_working_dir(
+        let mut harness = EditorTestHarness::with_shared_dir_context(
             80,
             24,
             Config::default(),
             project_dir.clone(),
+            dir_context.clone(),
         )
         .unwrap();
 
@@ -607,6 +694,7 @@ fn test_session_restores_cursor_in_splits() {
 #[test]
 fn test_session_restores_scroll_in_splits() {
     let temp_dir = TempDir::new().unwrap();
+    let dir_context = DirectoryContext::for_testing(&temp_dir.path().join("dirs"));
     let project_dir = temp_dir.path().join("project");
     std::fs::create_dir(&project_dir).unwrap();
 
@@ -622,11 +710,12 @@ fn test_session_restores_scroll_in_splits() {
 
     // First session: create splits and scroll both
     {
-        let mut harness = EditorTestHarness::with_config_and_working_dir(
+        let mut harness = EditorTestHarness::with_shared_dir_context(
             80,
             24,
             Config::default(),
             project_dir.clone(),
+            dir_context.clone(),
         )
         .unwrap();
 
@@ -654,11 +743,12 @@ fn test_session_restores_scroll_in_splits() {
 
     // Second session: restore and verify scroll positions
     {
-        let mut harness = EditorTestHarness::with_config_and_working_dir(
+        let mut harness = EditorTestHarness::with_shared_dir_context(
             80,
             24,
             Config::default(),
             project_dir.clone(),
+            dir_context.clone(),
         )
         .unwrap();
 
@@ -682,6 +772,7 @@ fn test_session_restores_scroll_in_splits() {
 #[test]
 fn test_session_cursor_visible_after_restore() {
     let temp_dir = TempDir::new().unwrap();
+    let dir_context = DirectoryContext::for_testing(&temp_dir.path().join("dirs"));
     let project_dir = temp_dir.path().join("project");
     std::fs::create_dir(&project_dir).unwrap();
 
@@ -694,11 +785,12 @@ fn test_session_cursor_visible_after_restore() {
 
     // First session: move cursor to middle of file (cursor visible, scroll follows)
     {
-        let mut harness = EditorTestHarness::with_config_and_working_dir(
+        let mut harness = EditorTestHarness::with_shared_dir_context(
             80,
             24,
             Config::default(),
             project_dir.clone(),
+            dir_context.clone(),
         )
         .unwrap();
 
@@ -731,11 +823,12 @@ fn test_session_cursor_visible_after_restore() {
 
     // Second session: restore and verify cursor is STILL visible
     {
-        let mut harness = EditorTestHarness::with_config_and_working_dir(
+        let mut harness = EditorTestHarness::with_shared_dir_context(
             80,
             24,
             Config::default(),
             project_dir.clone(),
+            dir_context.clone(),
         )
         .unwrap();
 
@@ -763,6 +856,7 @@ fn test_session_cursor_visible_after_restore() {
 #[test]
 fn test_session_cursor_visible_in_splits_after_restore() {
     let temp_dir = TempDir::new().unwrap();
+    let dir_context = DirectoryContext::for_testing(&temp_dir.path().join("dirs"));
     let project_dir = temp_dir.path().join("project");
     std::fs::create_dir(&project_dir).unwrap();
 
@@ -779,11 +873,12 @@ fn test_session_cursor_visible_in_splits_after_restore() {
     // First session: create split and move cursor to line 150
     // Using user's terminal size: 158 columns x 42 lines
     {
-        let mut harness = EditorTestHarness::with_config_and_working_dir(
+        let mut harness = EditorTestHarness::with_shared_dir_context(
             158,
             42,
             Config::default(),
             project_dir.clone(),
+            dir_context.clone(),
         )
         .unwrap();
 
@@ -835,11 +930,12 @@ fn test_session_cursor_visible_in_splits_after_restore() {
 
     // Second session: restore and verify cursor is visible in active split
     {
-        let mut harness = EditorTestHarness::with_config_and_working_dir(
+        let mut harness = EditorTestHarness::with_shared_dir_context(
             158,
             42,
             Config::default(),
             project_dir.clone(),
+            dir_context.clone(),
         )
         .unwrap();
 
@@ -956,6 +1052,7 @@ fn test_session_cursor_visible_in_splits_after_restore() {
 #[test]
 fn test_session_restores_files_when_plugin_buffer_was_active() {
     let temp_dir = TempDir::new().unwrap();
+    let dir_context = DirectoryContext::for_testing(&temp_dir.path().join("dirs"));
     let project_dir = temp_dir.path().join("project");
     std::fs::create_dir(&project_dir).unwrap();
 
@@ -964,11 +1061,12 @@ fn test_session_restores_files_when_plugin_buffer_was_active() {
 
     // First session: open a real file, then create a scratch buffer (simulates plugin buffer)
     {
-        let mut harness = EditorTestHarness::with_config_and_working_dir(
+        let mut harness = EditorTestHarness::with_shared_dir_context(
             80,
             24,
             Config::default(),
             project_dir.clone(),
+            dir_context.clone(),
         )
         .unwrap();
 
@@ -1006,11 +1104,12 @@ fn test_session_restores_files_when_plugin_buffer_was_active() {
 
     // Second session: restore and verify the real file is restored
     {
-        let mut harness = EditorTestHarness::with_config_and_working_dir(
+        let mut harness = EditorTestHarness::with_shared_dir_context(
             80,
             24,
             Config::default(),
             project_dir.clone(),
+            dir_context.clone(),
         )
         .unwrap();
 
@@ -1031,6 +1130,7 @@ fn test_session_restores_files_when_plugin_buffer_was_active() {
 #[test]
 fn test_session_restores_splits() {
     let temp_dir = TempDir::new().unwrap();
+    let dir_context = DirectoryContext::for_testing(&temp_dir.path().join("dirs"));
     let project_dir = temp_dir.path().join("project");
     std::fs::create_dir(&project_dir).unwrap();
 
@@ -1041,11 +1141,12 @@ fn test_session_restores_splits() {
 
     // First session: create two splits with different files
     {
-        let mut harness = EditorTestHarness::with_config_and_working_dir(
+        let mut harness = EditorTestHarness::with_shared_dir_context(
             80,
             24,
             Config::default(),
             project_dir.clone(),
+            dir_context.clone(),
         )
         .unwrap();
 
@@ -1075,11 +1176,12 @@ fn test_session_restores_splits() {
 
     // Second session: restore and verify splits are recreated
     {
-        let mut harness = EditorTestHarness::with_config_and_working_dir(
+        let mut harness = EditorTestHarness::with_shared_dir_context(
             80,
             24,
             Config::default(),
             project_dir.clone(),
+            dir_context.clone(),
         )
         .unwrap();
 
@@ -1103,3 +1205,240 @@ fn test_session_restores_splits() {
         harness.assert_buffer_content("Left split content");
     }
 }
+
+/// Test that a persistent plugin panel is restored in its split, and that it can be
+/// closed when the plugin that owns it is no longer loaded
+#[test]
+fn test_session_restores_persistent_panel_without_plugin() {
+    use fresh::services::plugins::api::PluginCommand;
+
+    let temp_dir = TempDir::new().unwrap();
+    let dir_context = DirectoryContext::for_testing(&temp_dir.path().join("dirs"));
+    let project_dir = temp_dir.path().join("project");
+    std::fs::create_dir(&project_dir).unwrap();
+
+    let file1 = project_dir.join("main.txt");
+    std::fs::write(&file1, "Main file content").unwrap();
+
+    // First session: a file and a persistent panel below it
+    {
+        let mut harness = EditorTestHarness::with_shared_dir_context(
+            80,
+            24,
+            Config::default(),
+            project_dir.clone(),
+            dir_context.clone(),
+        )
+        .unwrap();
+
+        harness.open_file(&file1).unwrap();
+        harness
+            .editor_mut()
+            .handle_plugin_command(PluginCommand::CreateVirtualBufferInSplit {
+                name: "*Results*".to_string(),
+                mode: "results-list".to_string(),
+                read_only: true,
+                entries: vec![fresh::primitives::text_property::TextPropertyEntry {
+                    text: "Live panel content\n".to_string(),
+                    properties: Default::default(),
+                }],
+                ratio: 0.5,
+                direction: None,
+                panel_id: Some("results".to_string()),
+                show_line_numbers: false,
+                show_cursors: true,
+                editing_disabled: true,
+                parse_ansi: false,
+                line_wrap: None,
+                persistent: true,
+                plugin: Some("results_plugin".to_string()),
+                request_id: None,
+            })
+            .unwrap();
+        harness.render().unwrap();
+        harness.assert_screen_contains("Live panel content");
+
+        let session = harness.editor().capture_session();
+        assert_eq!(session.panels.len(), 1);
+        assert_eq!(session.panels[0].plugin, "results_plugin");
+        assert_eq!(session.panels[0].panel_id, "results");
+        assert_eq!(session.panels[0].name, "*Results*");
+        assert_eq!(session.panels[0].mode, "results-list");
+
+        harness.editor_mut().save_session().unwrap();
+    }
+
+    // Second session: no plugin answers, so the placeholder explains why
+    {
+        let mut harness = EditorTestHarness::with_shared_dir_context(
+            80,
+            24,
+            Config::default(),
+            project_dir.clone(),
+            dir_context.clone(),
+        )
+        .unwrap();
+
+        harness.editor_mut().try_restore_session().unwrap();
+        harness.render().unwrap();
+
+        assert_eq!(harness.editor().get_split_count(), 2);
+        harness.assert_screen_contains("Main file content");
+        harness.assert_screen_contains("which is not loaded");
+        harness.assert_screen_not_contains("Live panel content");
+
+        // The panel was focused when the session was saved; q closes it
+        harness
+            .send_key(KeyCode::Char('q'), KeyModifiers::NONE)
+            .unwrap();
+        harness.render().unwrap();
+        harness.assert_screen_not_contains("which is not loaded");
+        assert!(harness.editor().capture_session().panels.is_empty());
+    }
+}
+
+/// Save a session of `files` in a fresh editor
+fn save_session_with_files(
+    project_dir: &std::path::Path,
+    dir_context: &DirectoryContext,
+    files: &[&std::path::Path],
+) {
+    let mut harness = EditorTestHarness::with_shared_dir_context(
+        80,
+        24,
+        Config::default(),
+        project_dir.to_path_buf(),
+        dir_context.clone(),
+    )
+    .unwrap();
+    for file in files {
+        harness.open_file(file).unwrap();
+    }
+    harness.editor_mut().save_session().unwrap();
+    // Snapshot names have millisecond resolution
+    std::thread::sleep(std::time::Duration::from_millis(2));
+}
+
+/// A session file that cannot be parsed is replaced by the newest snapshot
+#[test]
+fn test_corrupted_session_falls_back_to_snapshot() {
+    let temp_dir = TempDir::new().unwrap();
+    let dir_context = DirectoryContext::for_testing(&temp_dir.path().join("dirs"));
+    let project_dir = temp_dir.path().join("project");
+    std::fs::create_dir(&project_dir).unwrap();
+    let file = project_dir.join("notes.txt");
+    std::fs::write(&file, "Snapshot content").unwrap();
+
+    save_session_with_files(&project_dir, &dir_context, &[&file]);
+    let session_path = get_session_path(&dir_context.sessions_dir(), &project_dir);
+    std::fs::write(&session_path, "{\"version\": 1, \"working_dir\":").unwrap();
+
+    let mut harness = EditorTestHarness::with_shared_dir_context(
+        100,
+        24,
+        Config::default(),
+        project_dir.clone(),
+        dir_context.clone(),
+    )
+    .unwrap();
+    let restored = harness.editor_mut().try_restore_session().unwrap();
+    assert!(restored, "Snapshot should have been restored");
+    harness.assert_buffer_content("Snapshot content");
+    harness.render().unwrap();
+    harness.assert_screen_contains("Session file was unreadable");
+}
+
+/// "Restore Session from Snapshot" lists snapshots with their files and
+/// replaces the layout after confirming that modified buffers are discarded
+#[test]
+fn test_restore_session_from_snapshot_command() {
+    let temp_dir = TempDir::new().unwrap();
+    let dir_context = DirectoryContext::for_testing(&temp_dir.path().join("dirs"));
+    let project_dir = temp_dir.path().join("project");
+    std::fs::create_dir(&project_dir).unwrap();
+    let old_file = project_dir.join("old.txt");
+    let new_file = project_dir.join("new.txt");
+    let scratch = project_dir.join("scratch.txt");
+    std::fs::write(&old_file, "Old layout").unwrap();
+    std::fs::write(&new_file, "New layout").unwrap();
+    std::fs::write(&scratch, "").unwrap();
+
+    save_session_with_files(&project_dir, &dir_context, &[&old_file]);
+    save_session_with_files(&project_dir, &dir_context, &[&new_file]);
+
+    let mut harness = EditorTestHarness::with_shared_dir_context(
+        120,
+        30,
+        Config::default(),
+        project_dir.clone(),
+        dir_context.clone(),
+    )
+    .unwrap();
+    harness.open_file(&scratch).unwrap();
+    harness.type_text("unsaved").unwrap();
+
+    harness.editor_mut().start_restore_session_snapshot_prompt();
+    harness.render().unwrap();
+    harness.assert_screen_contains("1 buffers in");
+    harness.assert_screen_contains("new.txt");
+    harness.assert_screen_contains("old.txt");
+
+    // The older snapshot is second
+    harness.send_key(KeyCode::Down, KeyModifiers::NONE).unwrap();
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    harness.render().unwrap();
+    harness.assert_screen_contains("Unsaved changes in 1 buffer(s)");
+    harness.assert_buffer_content("unsaved");
+
+    harness.type_text("d").unwrap();
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    harness.render().unwrap();
+    harness.assert_buffer_content("Old layout");
+    harness.assert_screen_contains("Restored session snapshot");
+    harness.assert_screen_not_contains("scratch.txt");
+}
+
+/// Saving a session that hasn't changed since the last save writes nothing
+#[test]
+fn test_unchanged_session_save_writes_nothing() {
+    use fresh::session::SessionWriteSummary;
+
+    let temp_dir = TempDir::new().unwrap();
+    let dir_context = DirectoryContext::for_testing(&temp_dir.path().join("dirs"));
+    let project_dir = temp_dir.path().join("project");
+    std::fs::create_dir(&project_dir).unwrap();
+    let file1 = project_dir.join("a.txt");
+    let file2 = project_dir.join("b.txt");
+    std::fs::write(&file1, "Content of file A").unwrap();
+    std::fs::write(&file2, "Content of file B").unwrap();
+
+    let mut harness = EditorTestHarness::with_shared_dir_context(
+        80,
+        24,
+        Config::default(),
+        project_dir.clone(),
+        dir_context.clone(),
+    )
+    .unwrap();
+    harness.open_file(&file1).unwrap();
+    harness.open_file(&file2).unwrap();
+
+    let first = harness.editor_mut().save_session().unwrap();
+    assert!(first.session_written);
+    assert_eq!(first.file_states_written, 1);
+
+    let second = harness.editor_mut().save_session().unwrap();
+    assert_eq!(second, SessionWriteSummary::default());
+
+    // Moving the cursor rewrites the session and that file's state
+    harness
+        .send_key(KeyCode::Right, KeyModifiers::NONE)
+        .unwrap();
+    let third = harness.editor_mut().save_session().unwrap();
+    assert!(third.session_written);
+    assert_eq!(third.file_states_written, 1);
+}
@@ -0,0 +1,156 @@
+//! Tests for session ownership between instances open in the same directory
+//!
+//! Each harness stands for one editor instance; they share the state
+//! directory the way two processes in the same working directory would.
+
+use crate::common::harness::EditorTestHarness;
+use crossterm::event::{KeyCode, KeyModifiers};
+use fresh::app::SessionOwnership;
+use fresh::config::Config;
+use fresh::config_io::DirectoryContext;
+use fresh::session::{get_instance_marker_path, get_session_path, InstanceMarker};
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+/// A project with two files, and the state directory of its instances
+fn project() -> (TempDir, PathBuf, DirectoryContext) {
+    let temp_dir = TempDir::new().unwrap();
+    let dir_context = DirectoryContext::for_testing(&temp_dir.path().join("dirs"));
+    let project_dir = temp_dir.path().join("project");
+    std::fs::create_dir(&project_dir).unwrap();
+    std::fs::write(project_dir.join("first.txt"), "first").unwrap();
+    std::fs::write(project_dir.join("second.txt"), "second").unwrap();
+    (temp_dir, project_dir, dir_context)
+}
+
+/// Start an editor instance in `project_dir`
+fn instance(project_dir: &Path, dir_context: &DirectoryContext) -> EditorTestHarness {
+    EditorTestHarness::with_shared_dir_context(
+        160,
+        24,
+        Config::default(),
+        project_dir.to_path_buf(),
+        dir_context.clone(),
+    )
+    .unwrap()
+}
+
+/// An instance that owns the session and has saved it with `first.txt` open
+fn owner_with_saved_session(
+    project_dir: &Path,
+    dir_context: &DirectoryContext,
+) -> EditorTestHarness {
+    let mut owner = instance(project_dir, dir_context);
+    assert!(owner.editor_mut().claim_session_ownership(true));
+    owner.open_file(&project_dir.join("first.txt")).unwrap();
+    owner.editor_mut().save_session().unwrap();
+    owner
+}
+
+#[test]
+fn test_second_instance_never_writes_the_session() {
+    let (_temp_dir, project_dir, dir_context) = project();
+    let _owner = owner_with_saved_session(&project_dir, &dir_context);
+    let session_path = get_session_path(&dir_context.sessions_dir(), &project_dir);
+    let saved = std::fs::read_to_string(&session_path).unwrap();
+
+    let mut second = instance(&project_dir, &dir_context);
+    assert!(!second.editor_mut().claim_session_ownership(true));
+    second.render().unwrap();
+    second.assert_screen_contains("Continue (don't save session on exit)");
+    second.assert_screen_contains("Take over session ownership");
+    second.assert_screen_contains("Open without session");
+
+    // Continue restores the session without owning it
+    second.send_key(KeyCode::Enter, KeyModifiers::NONE).unwrap();
+    second.render().unwrap();
+    second.assert_screen_contains("first.txt");
+    assert_eq!(second.editor().session_ownership(), SessionOwnership::Guest);
+
+    // Editing still works, but the session file is never written
+    second.open_file(&project_dir.join("second.txt")).unwrap();
+    second.type_text("edited ").unwrap();
+    second.assert_buffer_content("edited second");
+    second.editor_mut().save_session().unwrap();
+    assert_eq!(std::fs::read_to_string(&session_path).unwrap(), saved);
+
+    second
+        .send_key(KeyCode::Char('p'), KeyModifiers::CONTROL)
+        .unwrap();
+    second.type_text("Show Session Ownership").unwrap();
+    second.send_key(KeyCode::Enter, KeyModifiers::NONE).unwrap();
+    second.render().unwrap();
+    second.assert_screen_contains("doesn't save the session");
+}
+
+#[test]
+fn test_taking_over_stops_the_previous_owner() {
+    let (_temp_dir, project_dir, dir_context) = project();
+    let mut owner = owner_with_saved_session(&project_dir, &dir_context);
+    let session_path = get_session_path(&dir_context.sessions_dir(), &project_dir);
+
+    let mut second = instance(&project_dir, &dir_context);
+    assert!(!second.editor_mut().claim_session_ownership(true));
+    second.render().unwrap();
+    second.send_key(KeyCode::Down, KeyModifiers::NONE).unwrap();
+    second.send_key(KeyCode::Enter, KeyModifiers::NONE).unwrap();
+    assert_eq!(second.editor().session_ownership(), SessionOwnership::Owner);
+    second.open_file(&project_dir.join("second.txt")).unwrap();
+    second.editor_mut().save_session().unwrap();
+    let saved = std::fs::read_to_string(&session_path).unwrap();
+    assert!(saved.contains("second.txt"));
+
+    // The previous owner notices and stops saving
+    owner.editor_mut().save_session().unwrap();
+    assert_eq!(std::fs::read_to_string(&session_path).unwrap(), saved);
+    assert_eq!(owner.editor().session_ownership(), SessionOwnership::Guest);
+    owner.render().unwrap();
+    // The warning wraps inside its notification
+    owner.assert_screen_contains("took over");
+}
+
+#[test]
+fn test_open_without_session() {
+    let (_temp_dir, project_dir, dir_context) = project();
+    let _owner = owner_with_saved_session(&project_dir, &dir_context);
+
+    let mut second = instance(&project_dir, &dir_context);
+    assert!(!second.editor_mut().claim_session_ownership(true));
+    second.render().unwrap();
+    second.send_key(KeyCode::Down, KeyModifiers::NONE).unwrap();
+    second.send_key(KeyCode::Down, KeyModifiers::NONE).unwrap();
+    second.send_key(KeyCode::Enter, KeyModifiers::NONE).unwrap();
+    second.render().unwrap();
+    second.assert_screen_not_contains("first.txt");
+    assert_eq!(
+        second.editor().session_ownership(),
+        SessionOwnership::Detached
+    );
+}
+
+#[test]
+fn test_stale_and_released_markers_are_claimed() {
+    let (_temp_dir, project_dir, dir_context) = project();
+    let sessions_dir = dir_context.sessions_dir();
+    let stale = InstanceMarker {
+        pid: u32::MAX / 2,
+        ..InstanceMarker::for_new_instance()
+    };
+    stale.write(&sessions_dir, &project_dir).unwrap();
+
+    // A marker left by a process that is gone is replaced without asking
+    let mut first = instance(&project_dir, &dir_context);
+    assert!(first.editor_mut().claim_session_ownership(true));
+    first.render().unwrap();
+    first.assert_screen_not_contains("Take over session ownership");
+    assert_ne!(
+        InstanceMarker::read_owner(&sessions_dir, &project_dir),
+        Some(stale)
+    );
+
+    // So is the session of an instance that exited
+    first.editor_mut().release_session_ownership();
+    assert!(!get_instance_marker_path(&sessions_dir, &project_dir).exists());
+    let mut second = instance(&project_dir, &dir_context);
+    assert!(second.editor_mut().claim_session_ownership(true));
+}
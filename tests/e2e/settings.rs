@@ -871,7 +871,10 @@ fn test_settings_from_terminal_mode_captures_input() {
     harness.assert_screen_contains("Settings");
 
     // Now try to use Settings navigation - press Down to navigate categories
-    harness.send_key(KeyCode::Down, KeyModifiers::NONE).unwrap();
+    // (past Clipboard and Distraction Free)
+    for _ in 0..3 {
+        harness.send_key(KeyCode::Down, KeyModifiers::NONE).unwrap();
+    }
     harness.render().unwrap();
 
     // The Settings should respond to navigation, not the terminal
@@ -1020,7 +1023,8 @@ fn test_settings_change_theme_and_save() {
         .unwrap();
     harness.render().unwrap();
 
-    // Cycle through theme options until we get to "light"
+    // Cycle through theme options until we get to "light" (the dropdown
+    // value, since the description mentions light themes too)
     let mut found_light = false;
     for _ in 0..10 {
         harness
@@ -1028,7 +1032,7 @@ fn test_settings_change_theme_and_save() {
             .unwrap();
         harness.render().unwrap();
 
-        if harness.screen_to_string().contains("light") {
+        if harness.screen_to_string().contains("[light ") {
             found_light = true;
             break;
         }
@@ -1077,14 +1081,27 @@ fn test_settings_descriptions_render_properly() {
         .unwrap();
     harness.render().unwrap();
 
-    // Navigate to Editor category which has settings with descriptions
-    harness.send_key(KeyCode::Down, KeyModifiers::NONE).unwrap();
+    // Navigate past Clipboard and Distraction Free to Editor category, which
+    // has settings with descriptions
+    for _ in 0..3 {
+        harness.send_key(KeyCode::Down, KeyModifiers::NONE).unwrap();
+    }
     harness.render().unwrap();
 
     // Switch to settings panel
     harness.send_key(KeyCode::Tab, KeyModifiers::NONE).unwrap();
     harness.render().unwrap();
 
+    // The "whether to enable" descriptions belong to the Enable* settings,
+    // which sit below the first page of Editor settings
+    for _ in 0..30 {
+        if harness.screen_to_string().contains("> Enable Inlay Hints") {
+            break;
+        }
+        harness.send_key(KeyCode::Down, KeyModifiers::NONE).unwrap();
+    }
+    harness.render().unwrap();
+
     let screen = harness.screen_to_string();
 
     // Check that descriptions are NOT cut off mid-word at the start
@@ -1286,8 +1303,11 @@ fn test_settings_percentage_value_saves_correctly() {
         .unwrap();
     harness.render().unwrap();
 
-    // Navigate to File Explorer category (down three times from General)
-    // Categories: General, Editor, File Browser, File Explorer, Menu, Terminal, Warnings
+    // Navigate to File Explorer category (down five times from General)
+    // Categories: General, Clipboard, Distraction Free, Editor, File Browser,
+    // File Explorer, ...
+    harness.send_key(KeyCode::Down, KeyModifiers::NONE).unwrap(); // Clipboard
+    harness.send_key(KeyCode::Down, KeyModifiers::NONE).unwrap(); // Distraction Free
     harness.send_key(KeyCode::Down, KeyModifiers::NONE).unwrap(); // Editor
     harness.send_key(KeyCode::Down, KeyModifiers::NONE).unwrap(); // File Browser
     harness.send_key(KeyCode::Down, KeyModifiers::NONE).unwrap(); // File Explorer
@@ -1298,8 +1318,14 @@ fn test_settings_percentage_value_saves_correctly() {
     harness.render().unwrap();
 
     // Navigate down to find the Width setting
-    // File Explorer settings: Custom Ignore Patterns, Respect Gitignore, Show Gitignored, Show Hidden, Width
+    // File Explorer settings: Custom Ignore Patterns, Max Visible Entries,
+    // Refresh Debounce Ms, Respect Gitignore, Show Details, Show Entry Counts,
+    // Show Gitignored, Show Hidden, Width
+    harness.send_key(KeyCode::Down, KeyModifiers::NONE).unwrap(); // Max Visible Entries
+    harness.send_key(KeyCode::Down, KeyModifiers::NONE).unwrap(); // Refresh Debounce Ms
     harness.send_key(KeyCode::Down, KeyModifiers::NONE).unwrap(); // Respect Gitignore
+    harness.send_key(KeyCode::Down, KeyModifiers::NONE).unwrap(); // Show Details
+    harness.send_key(KeyCode::Down, KeyModifiers::NONE).unwrap(); // Show Entry Counts
     harness.send_key(KeyCode::Down, KeyModifiers::NONE).unwrap(); // Show Gitignored
     harness.send_key(KeyCode::Down, KeyModifiers::NONE).unwrap(); // Show Hidden
     harness.send_key(KeyCode::Down, KeyModifiers::NONE).unwrap(); // Width
@@ -1810,8 +1836,10 @@ fn test_category_selection_indicator_visible() {
         screen
     );
 
-    // Navigate down to Editor category
-    harness.send_key(KeyCode::Down, KeyModifiers::NONE).unwrap();
+    // Navigate down past Clipboard and Distraction Free to Editor category
+    for _ in 0..3 {
+        harness.send_key(KeyCode::Down, KeyModifiers::NONE).unwrap();
+    }
     harness.render().unwrap();
 
     // Now Editor should have the ">" indicator
@@ -1948,8 +1976,8 @@ fn test_entry_dialog_focus_indicator() {
     harness.send_key(KeyCode::Down, KeyModifiers::NONE).unwrap();
     harness.render().unwrap();
 
-    // Now "Auto Indent" should be focused with ">" indicator
-    harness.assert_screen_contains("> Auto Indent");
+    // Now "Auto Complete" should be focused with ">" indicator
+    harness.assert_screen_contains("> Auto Complete");
 
     // Close dialog
     harness.send_key(KeyCode::Esc, KeyModifiers::NONE).unwrap();
@@ -1982,8 +2010,9 @@ fn test_entry_dialog_add_new_textlist_item() {
     harness.assert_screen_contains("Edit Value");
 
     // Navigate to Extensions section which has "[+] Add new"
-    // Fields in order: Key, Auto Indent, Comment Prefix, Extensions (3 downs)
-    for _ in 0..3 {
+    // Fields in order: Key, Auto Complete, Auto Indent, Comment Prefix,
+    // Completion Trigger Characters, Extensions (5 downs)
+    for _ in 0..5 {
         harness.send_key(KeyCode::Down, KeyModifiers::NONE).unwrap();
     }
     harness.render().unwrap();
@@ -2059,8 +2088,9 @@ fn test_entry_dialog_delete_textlist_item() {
     harness.assert_screen_contains("Edit Value");
 
     // Navigate to Extensions section which has existing items
-    // Fields in order: Key, Auto Indent, Comment Prefix, Extensions (3 downs)
-    for _ in 0..3 {
+    // Fields in order: Key, Auto Complete, Auto Indent, Comment Prefix,
+    // Completion Trigger Characters, Extensions (5 downs)
+    for _ in 0..5 {
         harness.send_key(KeyCode::Down, KeyModifiers::NONE).unwrap();
     }
     harness.render().unwrap();
@@ -2781,3 +2811,162 @@ fn test_settings_edit_button_blocked_with_pending_changes() {
         .send_key(KeyCode::Enter, KeyModifiers::NONE)
         .unwrap();
 }
+
+/// Toggle "Check For Updates" in the settings modal and save with Ctrl+S
+fn toggle_check_for_updates_and_save(harness: &mut EditorTestHarness) {
+    harness
+        .send_key(KeyCode::Char(','), KeyModifiers::CONTROL)
+        .unwrap();
+    harness
+        .send_key(KeyCode::Char('/'), KeyModifiers::NONE)
+        .unwrap();
+    harness.type_text("check").unwrap();
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    harness
+        .send_key(KeyCode::Char('s'), KeyModifiers::CONTROL)
+        .unwrap();
+    harness.render().unwrap();
+}
+
+/// Create a harness whose user config file is written before startup, then
+/// changed on disk behind the editor's back
+fn harness_with_externally_changed_config(
+) -> (EditorTestHarness, tempfile::TempDir, std::path::PathBuf) {
+    let temp = tempfile::TempDir::new().unwrap();
+    let dir_context = fresh::config_io::DirectoryContext::for_testing(temp.path());
+    let project = temp.path().join("project");
+    std::fs::create_dir_all(&project).unwrap();
+    let config_path = dir_context.config_path();
+    std::fs::create_dir_all(config_path.parent().unwrap()).unwrap();
+    std::fs::write(&config_path, r#"{"editor": {"tab_size": 2}}"#).unwrap();
+
+    let config = fresh::config::Config {
+        check_for_updates: false,
+        ..Default::default()
+    };
+    let mut harness =
+        EditorTestHarness::with_shared_dir_context(100, 40, config, project, dir_context).unwrap();
+    harness.render().unwrap();
+
+    std::fs::write(&config_path, r#"{"editor": {"tab_size": 7}}"#).unwrap();
+    (harness, temp, config_path)
+}
+
+/// Saving settings over a config file changed on disk asks before overwriting,
+/// and "Reload and reapply my change" keeps both edits
+#[test]
+fn test_settings_save_conflict_reload_and_reapply() {
+    let (mut harness, _temp, config_path) = harness_with_externally_changed_config();
+
+    toggle_check_for_updates_and_save(&mut harness);
+
+    // The write was refused and the file left alone
+    harness.assert_screen_contains("Config file changed on disk");
+    harness.assert_screen_contains("Reload and reapply my change");
+    assert_eq!(
+        std::fs::read_to_string(&config_path).unwrap(),
+        r#"{"editor": {"tab_size": 7}}"#
+    );
+
+    // First item: reload and reapply
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    harness.render().unwrap();
+
+    harness.assert_screen_not_contains("Config file changed on disk");
+    assert!(harness.config().check_for_updates);
+    assert_eq!(harness.config().editor.tab_size, 7);
+    let saved: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&config_path).unwrap()).unwrap();
+    assert_eq!(saved["editor"]["tab_size"], 7);
+}
+
+/// "Overwrite" replaces the file with the editor's config, keeping a backup
+#[test]
+fn test_settings_save_conflict_overwrite_keeps_backup() {
+    let (mut harness, _temp, config_path) = harness_with_externally_changed_config();
+
+    toggle_check_for_updates_and_save(&mut harness);
+    harness.assert_screen_contains("Config file changed on disk");
+
+    // Second item: overwrite
+    harness.send_key(KeyCode::Down, KeyModifiers::NONE).unwrap();
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    harness.render().unwrap();
+
+    assert!(harness.config().check_for_updates);
+    assert_eq!(harness.config().editor.tab_size, 4);
+    let saved: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&config_path).unwrap()).unwrap();
+    assert!(saved["editor"]["tab_size"].is_null());
+
+    let backups = fresh::config_io::config_backups(&config_path);
+    assert_eq!(backups.len(), 1);
+    assert_eq!(
+        std::fs::read_to_string(&backups[0]).unwrap(),
+        r#"{"editor": {"tab_size": 7}}"#
+    );
+}
+
+/// Settings renamed, retyped or unknown to this version are reconciled when the
+/// settings UI opens, and saving writes back the migrated config while keeping
+/// the unknown key
+#[test]
+fn test_settings_reconcile_outdated_user_config() {
+    let temp = tempfile::TempDir::new().unwrap();
+    let dir_context = fresh::config_io::DirectoryContext::for_testing(temp.path());
+    let project = temp.path().join("project");
+    std::fs::create_dir_all(&project).unwrap();
+    let config_path = dir_context.config_path();
+    std::fs::create_dir_all(config_path.parent().unwrap()).unwrap();
+    std::fs::write(
+        &config_path,
+        r#"{
+            "editor": { "tabSize": 2 },
+            "files": { "auto_revert": false },
+            "legacy_option": { "enabled": true }
+        }"#,
+    )
+    .unwrap();
+
+    let config = fresh::config::Config {
+        check_for_updates: false,
+        ..Default::default()
+    };
+    let mut harness =
+        EditorTestHarness::with_shared_dir_context(100, 40, config, project, dir_context).unwrap();
+    harness
+        .send_key(KeyCode::Char(','), KeyModifiers::CONTROL)
+        .unwrap();
+    harness.render().unwrap();
+    harness.assert_screen_contains("Unrecognized");
+
+    // The migrations are pending, so saving writes them back
+    harness
+        .send_key(KeyCode::Char('s'), KeyModifiers::CONTROL)
+        .unwrap();
+    harness.render().unwrap();
+
+    assert_eq!(harness.config().editor.tab_size, 2);
+    assert_eq!(
+        harness.config().files.auto_revert,
+        fresh::config::AutoRevertMode::Off
+    );
+    let saved: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&config_path).unwrap()).unwrap();
+    assert_eq!(saved["editor"]["tab_size"], 2);
+    assert!(saved["editor"].get("tabSize").is_none());
+    assert_eq!(saved["files"]["auto_revert"], "off");
+    assert_eq!(
+        saved["legacy_option"],
+        serde_json::json!({ "enabled": true })
+    );
+}
@@ -0,0 +1,146 @@
+//! E2E tests for exporting and importing settings archives
+
+use crate::common::harness::EditorTestHarness;
+use crossterm::event::{KeyCode, KeyModifiers};
+use fresh::config::Config;
+use fresh::config_io::DirectoryContext;
+use fresh::services::settings_archive::{self, ArchiveCategory, SettingsArchive};
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+/// A directory context with a user config, a theme and some search history
+fn settings_dirs(root: &Path, tab_size: usize) -> DirectoryContext {
+    let dir_context = DirectoryContext::for_testing(root);
+    std::fs::create_dir_all(dir_context.config_dir.join("themes")).unwrap();
+    std::fs::create_dir_all(&dir_context.state_dir).unwrap();
+    std::fs::write(
+        dir_context.config_dir.join("config.json"),
+        format!(r#"{{"editor": {{"tab_size": {tab_size}}}}}"#),
+    )
+    .unwrap();
+    std::fs::write(
+        dir_context.config_dir.join("themes").join("mine.json"),
+        r#"{"name": "mine"}"#,
+    )
+    .unwrap();
+    std::fs::write(
+        dir_context.search_history_path(),
+        r#"{"items": ["needle"]}"#,
+    )
+    .unwrap();
+    dir_context
+}
+
+fn start(dir_context: &DirectoryContext, project_dir: PathBuf) -> EditorTestHarness {
+    std::fs::create_dir_all(&project_dir).unwrap();
+    let config = Config::load_with_layers(dir_context, &project_dir);
+    EditorTestHarness::with_shared_dir_context(100, 30, config, project_dir, dir_context.clone())
+        .unwrap()
+}
+
+fn press(harness: &mut EditorTestHarness, code: KeyCode, times: usize) {
+    for _ in 0..times {
+        harness.send_key(code, KeyModifiers::NONE).unwrap();
+    }
+}
+
+/// Checking an opt-in category in the export popup adds its files to the
+/// archive written to the default path
+#[test]
+fn test_export_with_opt_in_category() {
+    let temp_dir = TempDir::new().unwrap();
+    let dir_context = settings_dirs(&temp_dir.path().join("dirs"), 3);
+    let project_dir = temp_dir.path().join("project");
+    let mut harness = start(&dir_context, project_dir.clone());
+
+    harness.editor_mut().show_settings_export_popup(0);
+    harness.render().unwrap();
+    harness.assert_screen_contains("Export Settings Archive");
+    harness.assert_screen_contains("may contain file paths");
+
+    // Check "history", the last category, then choose "Export…"
+    let history = ArchiveCategory::ALL
+        .iter()
+        .position(|c| *c == ArchiveCategory::History)
+        .unwrap();
+    press(&mut harness, KeyCode::Down, history);
+    press(&mut harness, KeyCode::Enter, 1);
+    harness.assert_screen_contains("Export Settings Archive");
+    press(&mut harness, KeyCode::Down, 1);
+    press(&mut harness, KeyCode::Enter, 1);
+    assert!(harness.editor().is_prompting());
+    press(&mut harness, KeyCode::Enter, 1);
+
+    let archive_path = project_dir.join(settings_archive::DEFAULT_FILE_NAME);
+    let archive = SettingsArchive::read(&archive_path).unwrap();
+    assert!(archive
+        .manifest
+        .categories
+        .contains(&ArchiveCategory::History));
+    assert!(!archive
+        .manifest
+        .categories
+        .contains(&ArchiveCategory::Sessions));
+    for file in [
+        "config/config.json",
+        "config/themes/mine.json",
+        "state/search_history.json",
+    ] {
+        assert!(
+            archive.manifest.files.iter().any(|f| f == file),
+            "{file} missing from {:?}",
+            archive.manifest.files
+        );
+    }
+    let status = harness.editor().get_status_message().cloned().unwrap();
+    assert!(status.contains("Exported 3 file(s)"), "status: {status}");
+}
+
+/// Importing shows the files it would replace, backs them up, writes the
+/// archive's versions and offers a restart for the changed config
+#[test]
+fn test_import_previews_and_backs_up() {
+    let temp_dir = TempDir::new().unwrap();
+    let source = settings_dirs(&temp_dir.path().join("source"), 3);
+    let target = settings_dirs(&temp_dir.path().join("target"), 8);
+    let project_dir = temp_dir.path().join("project");
+    std::fs::create_dir_all(&project_dir).unwrap();
+    std::fs::write(
+        source.config_dir.join("themes").join("other.json"),
+        r#"{"name": "other"}"#,
+    )
+    .unwrap();
+    settings_archive::export(
+        &source,
+        &ArchiveCategory::defaults(),
+        &project_dir.join(settings_archive::DEFAULT_FILE_NAME),
+    )
+    .unwrap();
+
+    let mut harness = start(&target, project_dir);
+    harness.editor_mut().start_settings_import_prompt();
+    press(&mut harness, KeyCode::Enter, 1);
+    harness.render().unwrap();
+    harness.assert_screen_contains("Import Settings Archive");
+    harness.assert_screen_contains("1 new, 1 replaced, 1 unchanged");
+    harness.assert_screen_contains("config/config.json");
+
+    press(&mut harness, KeyCode::Enter, 1);
+    harness.render().unwrap();
+    assert_eq!(
+        std::fs::read_to_string(target.config_dir.join("config.json")).unwrap(),
+        r#"{"editor": {"tab_size": 3}}"#
+    );
+    assert!(target.config_dir.join("themes").join("other.json").exists());
+
+    let backups: Vec<_> = std::fs::read_dir(target.settings_backups_dir())
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .collect();
+    assert_eq!(backups.len(), 1);
+    assert_eq!(
+        std::fs::read_to_string(backups[0].join("config").join("config.json")).unwrap(),
+        r#"{"editor": {"tab_size": 8}}"#
+    );
+    harness.assert_screen_contains("Restart Now");
+}
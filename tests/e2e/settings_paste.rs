@@ -69,3 +69,42 @@ fn test_settings_paste() {
     // Verify content is pasted
     harness.assert_screen_contains("rust");
 }
+
+/// Bracketed paste goes to the settings field being edited, replacing the
+/// selection, with the trailing newline stripped
+#[test]
+fn test_settings_bracketed_paste_replaces_selection() {
+    let mut harness = EditorTestHarness::new(100, 40).unwrap();
+
+    // Open settings and the "Add Language" dialog
+    harness
+        .send_key(KeyCode::Char(','), KeyModifiers::CONTROL)
+        .unwrap();
+    harness
+        .send_key(KeyCode::Char('/'), KeyModifiers::NONE)
+        .unwrap();
+    send_text(&mut harness, "languages");
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    harness.render().unwrap();
+    harness.assert_screen_contains("Key");
+
+    // Start editing the "Key" field and select its contents
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    harness
+        .send_key(KeyCode::Char('a'), KeyModifiers::CONTROL)
+        .unwrap();
+
+    harness.editor_mut().paste_text("zig-lang\n".to_string());
+    harness.render().unwrap();
+    harness.assert_screen_contains("[zig-lang");
+
+    // The paste went to the settings field, not the buffer behind the modal
+    assert_eq!(harness.get_buffer_content().unwrap(), "");
+}
@@ -809,7 +809,12 @@ fn apply_test_diagnostics(
 ) {
     let state = harness.editor_mut().active_state_mut();
     let theme = fresh::view::theme::Theme::dark();
-    fresh::services::lsp::diagnostics::apply_diagnostics_to_state(state, &diagnostics, &theme);
+    fresh::services::lsp::diagnostics::apply_diagnostics_to_state(
+        state,
+        &diagnostics,
+        fresh::model::position_encoding::PositionEncoding::Utf16,
+        &theme,
+    );
 }
 
 /// Create a simple diagnostic at a given position
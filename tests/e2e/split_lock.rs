@@ -0,0 +1,134 @@
+//! Tests for the per-split input lock ("Toggle Pane Lock")
+
+use crate::common::harness::EditorTestHarness;
+use crossterm::event::{KeyCode, KeyModifiers};
+use fresh::config::Config;
+use fresh::config_io::DirectoryContext;
+use tempfile::TempDir;
+
+/// Run a command from the command palette
+fn run_command(harness: &mut EditorTestHarness, name: &str) {
+    harness
+        .send_key(KeyCode::Char('p'), KeyModifiers::CONTROL)
+        .unwrap();
+    harness.type_text(name).unwrap();
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    harness.render().unwrap();
+}
+
+#[test]
+fn test_locked_pane_refuses_edits_that_another_split_allows() {
+    let mut harness = EditorTestHarness::new(100, 24).unwrap();
+    harness.type_text("hello").unwrap();
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    harness.type_text("world").unwrap();
+
+    // The new split shows the same buffer; lock it
+    run_command(&mut harness, "Split Vertical");
+    run_command(&mut harness, "Toggle Pane Lock");
+    assert!(harness.get_status_bar().contains("🔒"));
+
+    // Typing, deleting, pasting and undo change nothing
+    harness.type_text("xyz").unwrap();
+    harness
+        .send_key(KeyCode::Delete, KeyModifiers::NONE)
+        .unwrap();
+    harness.editor_mut().paste_text("pasted".to_string());
+    harness
+        .send_key(KeyCode::Char('z'), KeyModifiers::CONTROL)
+        .unwrap();
+    harness.render().unwrap();
+    harness.assert_buffer_content("hello\nworld");
+    harness.assert_screen_contains("Pane is locked");
+
+    // Moving still works, and so does adding cursors, but not typing at them
+    harness.send_key(KeyCode::End, KeyModifiers::NONE).unwrap();
+    let position = harness.cursor_position();
+    assert_ne!(position, 0);
+    run_command(&mut harness, "Add Cursor Below");
+    harness.type_text("!").unwrap();
+    harness.assert_buffer_content("hello\nworld");
+
+    // The other split edits the same buffer as usual
+    run_command(&mut harness, "Next Split");
+    harness.type_text("!").unwrap();
+    harness.assert_buffer_content("hello\nworld!");
+    assert!(!harness.get_status_bar().contains("🔒"));
+
+    // Unlocking the first split makes it editable again
+    run_command(&mut harness, "Next Split");
+    run_command(&mut harness, "Toggle Pane Lock");
+    harness.assert_screen_contains("Pane unlocked");
+    harness.send_key(KeyCode::Home, KeyModifiers::NONE).unwrap();
+    harness.type_text(">").unwrap();
+    assert!(harness.get_buffer_content().unwrap().starts_with(">hello"));
+}
+
+#[test]
+fn test_pane_lock_is_restored_with_the_session() {
+    let temp_dir = TempDir::new().unwrap();
+    let dir_context = DirectoryContext::for_testing(&temp_dir.path().join("dirs"));
+    let project_dir = temp_dir.path().join("project");
+    std::fs::create_dir(&project_dir).unwrap();
+    let file = project_dir.join("reference.txt");
+    std::fs::write(&file, "reference").unwrap();
+
+    {
+        let mut harness = EditorTestHarness::with_shared_dir_context(
+            100,
+            24,
+            Config::default(),
+            project_dir.clone(),
+            dir_context.clone(),
+        )
+        .unwrap();
+        harness.open_file(&file).unwrap();
+        run_command(&mut harness, "Split Vertical");
+        run_command(&mut harness, "Toggle Pane Lock");
+        harness.editor_mut().save_session().unwrap();
+    }
+
+    let mut harness = EditorTestHarness::with_shared_dir_context(
+        100,
+        24,
+        Config::default(),
+        project_dir,
+        dir_context,
+    )
+    .unwrap();
+    harness.editor_mut().try_restore_session().unwrap();
+    harness.render().unwrap();
+
+    // The restored active split is still locked, the other one isn't
+    harness.type_text("x").unwrap();
+    harness.assert_buffer_content("reference");
+    run_command(&mut harness, "Next Split");
+    harness.type_text("x").unwrap();
+    harness.assert_buffer_content("xreference");
+}
+
+#[test]
+fn test_locked_pane_refuses_revert() {
+    let temp_dir = TempDir::new().unwrap();
+    let file = temp_dir.path().join("notes.txt");
+    std::fs::write(&file, "saved").unwrap();
+
+    let mut harness = EditorTestHarness::new(100, 24).unwrap();
+    harness.open_file(&file).unwrap();
+    harness.type_text("x").unwrap();
+    run_command(&mut harness, "Split Vertical");
+    run_command(&mut harness, "Toggle Pane Lock");
+
+    // Both reverts are stopped before they ask to discard the changes
+    for command in ["Revert Buffer to Saved", "Revert File"] {
+        harness.editor_mut().set_status_message(String::new());
+        run_command(&mut harness, command);
+        assert!(!harness.editor().is_prompting(), "{command} prompted");
+        harness.assert_screen_contains("Pane is locked");
+        harness.assert_buffer_content("xsaved");
+    }
+}
@@ -0,0 +1,131 @@
+//! Tests for searches kept per split
+
+use crate::common::harness::EditorTestHarness;
+use crossterm::event::{KeyCode, KeyModifiers};
+use fresh::view::theme::Theme;
+use tempfile::TempDir;
+
+const TEXT: &str = "alpha beta\nbeta gamma\nalpha beta alpha\n";
+
+/// Run a command from the command palette
+fn run_command(harness: &mut EditorTestHarness, name: &str) {
+    harness
+        .send_key(KeyCode::Char('p'), KeyModifiers::CONTROL)
+        .unwrap();
+    harness.type_text(name).unwrap();
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    harness.render().unwrap();
+}
+
+/// Search the focused split from the start of the buffer
+fn search(harness: &mut EditorTestHarness, query: &str) {
+    harness
+        .send_key(KeyCode::Home, KeyModifiers::CONTROL)
+        .unwrap();
+    harness
+        .send_key(KeyCode::Char('f'), KeyModifiers::CONTROL)
+        .unwrap();
+    harness.type_text(query).unwrap();
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    harness.render().unwrap();
+}
+
+/// Number of on-screen occurrences of `word` drawn as search matches in the
+/// left and the right half of the screen
+fn highlighted(harness: &EditorTestHarness, word: &str) -> (usize, usize) {
+    let match_bg = Theme::high_contrast().search_match_bg;
+    let half = harness.buffer().area.width / 2;
+    let mut counts = (0, 0);
+    for (y, line) in harness.screen_to_string().lines().enumerate() {
+        let chars: Vec<char> = line.chars().collect();
+        let word: Vec<char> = word.chars().collect();
+        for x in 0..chars.len().saturating_sub(word.len() - 1) {
+            if chars[x..x + word.len()] != word[..] {
+                continue;
+            }
+            // The second character, since a cursor may sit on the first
+            let style = harness.get_cell_style(x as u16 + 1, y as u16).unwrap();
+            if style.bg == Some(match_bg) {
+                if (x as u16) < half {
+                    counts.0 += 1;
+                } else {
+                    counts.1 += 1;
+                }
+            }
+        }
+    }
+    counts
+}
+
+#[test]
+fn test_splits_keep_independent_searches() {
+    let temp_dir = TempDir::new().unwrap();
+    let file_path = temp_dir.path().join("test.txt");
+    std::fs::write(&file_path, TEXT).unwrap();
+
+    let mut harness = EditorTestHarness::new(100, 24).unwrap();
+    harness.open_file(&file_path).unwrap();
+
+    // The new split (on the right) searches for "beta", the left one for "alpha"
+    run_command(&mut harness, "Split Vertical");
+    search(&mut harness, "beta");
+    run_command(&mut harness, "Next Split");
+    search(&mut harness, "alpha");
+
+    assert_eq!(highlighted(&harness, "alpha"), (3, 0));
+    assert_eq!(highlighted(&harness, "beta"), (0, 3));
+
+    // Find Next follows the focused split's search only
+    assert_eq!(harness.cursor_position(), 0);
+    harness.send_key(KeyCode::F(3), KeyModifiers::NONE).unwrap();
+    assert_eq!(harness.cursor_position(), TEXT.rfind("alpha b").unwrap());
+    harness.render().unwrap();
+    assert!(harness.get_status_bar().contains("Search 2/3"));
+
+    run_command(&mut harness, "Next Split");
+    assert_eq!(harness.cursor_position(), TEXT.find("beta").unwrap());
+    assert!(harness.get_status_bar().contains("Search 1/3"));
+    harness.send_key(KeyCode::F(3), KeyModifiers::NONE).unwrap();
+    assert_eq!(harness.cursor_position(), TEXT.find("beta gamma").unwrap());
+    harness
+        .send_key(KeyCode::F(3), KeyModifiers::SHIFT)
+        .unwrap();
+    assert_eq!(harness.cursor_position(), TEXT.find("beta").unwrap());
+
+    // The left split resumes where it left off
+    run_command(&mut harness, "Next Split");
+    harness.send_key(KeyCode::F(3), KeyModifiers::NONE).unwrap();
+    assert_eq!(harness.cursor_position(), TEXT.rfind("alpha").unwrap());
+    harness.render().unwrap();
+    assert_eq!(highlighted(&harness, "alpha"), (3, 0));
+    assert_eq!(highlighted(&harness, "beta"), (0, 3));
+}
+
+#[test]
+fn test_search_in_all_splits_propagates_the_focused_search() {
+    let temp_dir = TempDir::new().unwrap();
+    let file_path = temp_dir.path().join("test.txt");
+    std::fs::write(&file_path, TEXT).unwrap();
+
+    let mut harness = EditorTestHarness::new(100, 24).unwrap();
+    harness.open_file(&file_path).unwrap();
+
+    run_command(&mut harness, "Split Vertical");
+    search(&mut harness, "beta");
+    run_command(&mut harness, "Next Split");
+    search(&mut harness, "alpha");
+
+    run_command(&mut harness, "Search in All Splits");
+    assert_eq!(highlighted(&harness, "alpha"), (3, 3));
+    assert_eq!(highlighted(&harness, "beta"), (0, 0));
+
+    // The other split now steps through "alpha" matches from its cursor,
+    // which stayed on the first "beta"
+    run_command(&mut harness, "Next Split");
+    harness.send_key(KeyCode::F(3), KeyModifiers::NONE).unwrap();
+    assert_eq!(harness.cursor_position(), TEXT.rfind("alpha b").unwrap());
+}
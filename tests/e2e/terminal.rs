@@ -939,6 +939,7 @@ fn test_terminal_resize_cursor_out_of_bounds() {
 #[test]
 fn test_session_restore_terminal_active_buffer() {
     use fresh::config::Config;
+    use fresh::config_io::DirectoryContext;
     use portable_pty::{native_pty_system, PtySize};
     use tempfile::TempDir;
 
@@ -957,6 +958,7 @@ fn test_session_restore_terminal_active_buffer() {
     }
 
     let temp_dir = TempDir::new().unwrap();
+    let dir_context = DirectoryContext::for_testing(&temp_dir.path().join("dirs"));
     let project_dir = temp_dir.path().join("project");
     std::fs::create_dir(&project_dir).unwrap();
 
@@ -966,11 +968,12 @@ fn test_session_restore_terminal_active_buffer() {
 
     // First session: open file, open terminal, terminal should be active
     {
-        let mut harness = EditorTestHarness::with_config_and_working_dir(
+        let mut harness = EditorTestHarness::with_shared_dir_context(
             80,
             24,
             Config::default(),
             project_dir.clone(),
+            dir_context.clone(),
         )
         .unwrap();
 
@@ -1018,11 +1021,12 @@ fn test_session_restore_terminal_active_buffer() {
 
     // Second session: restore and verify terminal is still active
     {
-        let mut harness = EditorTestHarness::with_config_and_working_dir(
+        let mut harness = EditorTestHarness::with_shared_dir_context(
             80,
             24,
             Config::default(),
             project_dir.clone(),
+            dir_context.clone(),
         )
         .unwrap();
 
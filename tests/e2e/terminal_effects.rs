@@ -0,0 +1,89 @@
+//! E2E tests for the escape sequences written between frames
+
+use crate::common::harness::EditorTestHarness;
+use crossterm::event::{KeyCode, KeyModifiers};
+use fresh::config::CursorStyle;
+use fresh::services::plugins::api::PluginCommand;
+use fresh::view::terminal_effects::{write_effects, TerminalEffect};
+use std::time::{Duration, Instant};
+
+fn execute_action(harness: &mut EditorTestHarness, name: &str) {
+    harness
+        .editor_mut()
+        .handle_plugin_command(PluginCommand::ExecuteAction {
+            action_name: name.to_string(),
+        })
+        .unwrap();
+    harness.render().unwrap();
+}
+
+fn set_plugin_cursor_style(harness: &mut EditorTestHarness, style: &str) {
+    harness
+        .editor_mut()
+        .handle_plugin_command(PluginCommand::SetCursorStyle {
+            style: Some(style.to_string()),
+        })
+        .unwrap();
+}
+
+/// A cursor style change is written once, as one sequence, and a second
+/// change right after waits for the next frame past the rate limit
+#[test]
+fn test_cursor_style_is_written_once_per_change() {
+    let mut harness = EditorTestHarness::new(80, 24).unwrap();
+    let now = Instant::now();
+
+    set_plugin_cursor_style(&mut harness, "steady_bar");
+    let effects = harness.editor_mut().take_terminal_effects(now);
+    assert_eq!(
+        effects,
+        vec![TerminalEffect::SetCursorStyle(CursorStyle::SteadyBar)]
+    );
+    let mut out = Vec::new();
+    write_effects(&mut out, &effects).unwrap();
+    assert_eq!(out, b"\x1b[6 q");
+    assert_eq!(harness.editor_mut().take_terminal_effects(now), vec![]);
+
+    set_plugin_cursor_style(&mut harness, "steady_block");
+    set_plugin_cursor_style(&mut harness, "blinking_underline");
+    assert_eq!(harness.editor_mut().take_terminal_effects(now), vec![]);
+    assert_eq!(
+        harness
+            .editor_mut()
+            .take_terminal_effects(now + Duration::from_millis(50)),
+        vec![TerminalEffect::SetCursorStyle(
+            CursorStyle::BlinkingUnderline
+        )]
+    );
+}
+
+/// Effects queued during an iteration come out together, in order, with
+/// repeated toggles collapsed to the last one
+#[test]
+fn test_frame_effects_in_order() {
+    let mut harness = EditorTestHarness::new(80, 24).unwrap();
+    let now = Instant::now();
+    harness.editor_mut().take_terminal_effects(now);
+
+    execute_action(&mut harness, "toggle_mouse_capture");
+    execute_action(&mut harness, "toggle_mouse_capture");
+    execute_action(&mut harness, "toggle_mouse_capture");
+    harness.type_text("hello").unwrap();
+    harness
+        .send_key(KeyCode::Home, KeyModifiers::SHIFT)
+        .unwrap();
+    harness
+        .send_key(KeyCode::Char('c'), KeyModifiers::CONTROL)
+        .unwrap();
+    harness.send_key(KeyCode::End, KeyModifiers::NONE).unwrap();
+
+    assert_eq!(
+        harness
+            .editor_mut()
+            .take_terminal_effects(now + Duration::from_secs(1)),
+        vec![
+            TerminalEffect::MouseCapture(false),
+            TerminalEffect::Osc52Copy("hello".to_string()),
+        ]
+    );
+}
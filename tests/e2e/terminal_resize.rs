@@ -1,4 +1,6 @@
 use crate::common::harness::EditorTestHarness;
+use crossterm::event::{KeyCode, KeyModifiers};
+use fresh::services::plugins::api::{ActionPopupAction, PluginCommand};
 use tempfile::TempDir;
 
 /// Test that viewport uses full available area after terminal resize at startup
@@ -143,3 +145,153 @@ fn test_viewport_updates_after_resize_with_open_file() {
         content_lines_after.len()
     );
 }
+
+/// Test the fallback size poll for terminals that never send resize events
+///
+/// The event loop periodically compares the real terminal size against the
+/// editor's cached size; a mismatch must resize the editor just like a resize
+/// event would, and a match must be a no-op.
+#[test]
+fn test_terminal_size_poll_applies_missed_resize() {
+    let temp_dir = TempDir::new().unwrap();
+    let file_path = temp_dir.path().join("test_file.txt");
+    let content: String = (1..=50).map(|i| format!("Line {}\n", i)).collect();
+    std::fs::write(&file_path, content).unwrap();
+
+    let mut harness = EditorTestHarness::new(80, 15).unwrap();
+    harness.open_file(&file_path).unwrap();
+
+    // Same size as cached: nothing to do
+    assert!(!harness.editor_mut().check_terminal_size(80, 15));
+
+    // The terminal grew but no resize event arrived
+    harness.resize_backend_only(80, 30);
+    assert!(harness.editor_mut().check_terminal_size(80, 30));
+    harness.render().unwrap();
+
+    let info = harness.editor().terminal_info();
+    assert_eq!((info.width, info.height), (80, 30));
+    harness.assert_screen_contains("Line 25");
+}
+
+/// Below the minimum size the editor says so instead of drawing itself,
+/// and draws itself again once there's room
+#[test]
+fn test_too_small_terminal_shows_placeholder() {
+    let mut harness = EditorTestHarness::new(80, 24).unwrap();
+    let _fixture = harness.load_buffer_from_text("fn main() {}\n").unwrap();
+    harness.render().unwrap();
+    harness.assert_screen_contains("fn main");
+
+    harness.resize(12, 3).unwrap();
+    harness.assert_screen_contains("Terminal");
+    harness.assert_screen_not_contains("fn main");
+
+    harness.resize(1, 1).unwrap();
+    harness.resize(0, 0).unwrap();
+
+    harness.resize(80, 24).unwrap();
+    harness.assert_screen_contains("fn main");
+}
+
+/// Small deterministic generator for the resize sequences
+struct Rng(u64);
+
+impl Rng {
+    fn below(&mut self, n: u64) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0 % n
+    }
+}
+
+fn show_action_popup(harness: &mut EditorTestHarness) {
+    harness
+        .editor_mut()
+        .handle_plugin_command(PluginCommand::ShowActionPopup {
+            popup_id: "resize".to_string(),
+            title: "Resize".to_string(),
+            message: "Still here?".to_string(),
+            actions: vec![ActionPopupAction {
+                id: "yes".to_string(),
+                label: "Yes".to_string(),
+            }],
+        })
+        .unwrap();
+}
+
+fn execute_action(harness: &mut EditorTestHarness, name: &str) {
+    harness
+        .editor_mut()
+        .handle_plugin_command(PluginCommand::ExecuteAction {
+            action_name: name.to_string(),
+        })
+        .unwrap();
+}
+
+/// Random resizes down to 1x1 and back, with splits, popups, prompts and
+/// the file explorer coming and going, never panic, and the full-size frame
+/// afterwards shows the file again
+#[test]
+fn test_random_resizes_down_to_one_cell() {
+    let temp_dir = TempDir::new().unwrap();
+    let file_path = temp_dir.path().join("main.rs");
+    let content: String = (1..=200)
+        .map(|i| format!("fn function_{}() {{ let value = {}; }}\n", i, i))
+        .collect();
+    std::fs::write(&file_path, content).unwrap();
+
+    for seed in [0x9e37_79b9_7f4a_7c15, 0x2545_f491_4f6c_dd1d, 42] {
+        let mut rng = Rng(seed);
+        let mut harness = EditorTestHarness::new(100, 30).unwrap();
+        harness.open_file(&file_path).unwrap();
+        let buffer_id = harness.editor().active_buffer();
+
+        for _ in 0..150 {
+            match rng.below(10) {
+                0 if harness.editor().get_split_count() < 6 => {
+                    execute_action(&mut harness, "split_vertical")
+                }
+                1 if harness.editor().get_split_count() < 6 => {
+                    execute_action(&mut harness, "split_horizontal")
+                }
+                2 => show_action_popup(&mut harness),
+                3 => harness
+                    .editor_mut()
+                    .handle_plugin_command(PluginCommand::ShowAnchoredPopup {
+                        buffer_id,
+                        position: 0,
+                        lines: vec!["hover text".to_string()],
+                        max_width: 40,
+                        timeout_ms: None,
+                    })
+                    .unwrap(),
+                4 => execute_action(&mut harness, "command_palette"),
+                5 => execute_action(&mut harness, "toggle_file_explorer"),
+                6 => harness.send_key(KeyCode::Esc, KeyModifiers::NONE).unwrap(),
+                _ => {}
+            }
+
+            // Mostly tiny sizes, sometimes roomy ones
+            let (width, height) = if rng.below(2) == 0 {
+                (1 + rng.below(24), 1 + rng.below(8))
+            } else {
+                (1 + rng.below(120), 1 + rng.below(40))
+            };
+            harness.resize(width as u16, height as u16).unwrap();
+        }
+
+        for _ in 0..3 {
+            harness.send_key(KeyCode::Esc, KeyModifiers::NONE).unwrap();
+        }
+        harness.resize(100, 30).unwrap();
+        harness.editor_mut().switch_buffer(buffer_id);
+        // Nested splits can leave the active one too narrow for a whole line
+        if harness.editor().get_split_count() > 1 {
+            execute_action(&mut harness, "toggle_maximize_split");
+        }
+        harness.render().unwrap();
+        harness.assert_screen_contains("fn function_1()");
+    }
+}
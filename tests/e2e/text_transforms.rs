@@ -0,0 +1,142 @@
+//! E2E tests for the sort, unique, reverse and case conversion commands
+
+use crate::common::harness::EditorTestHarness;
+use crossterm::event::{KeyCode, KeyModifiers};
+use fresh::services::plugins::api::PluginCommand;
+
+fn execute_action(harness: &mut EditorTestHarness, name: &str) {
+    harness
+        .editor_mut()
+        .handle_plugin_command(PluginCommand::ExecuteAction {
+            action_name: name.to_string(),
+        })
+        .unwrap();
+    harness.render().unwrap();
+}
+
+/// Sorting a selection sorts the whole lines it touches, leaves the text
+/// around them exactly as it was, and keeps them selected
+#[test]
+fn test_sort_selected_lines_preserves_unselected_text() {
+    let mut harness = EditorTestHarness::new(80, 24).unwrap();
+    let original = "header \n  zeta\nalpha\n10\n9\nfooter\n\n";
+    let _fixture = harness.load_buffer_from_text(original).unwrap();
+
+    // From the middle of "  zeta" to the middle of "9"
+    harness.send_key(KeyCode::Down, KeyModifiers::NONE).unwrap();
+    harness
+        .send_key(KeyCode::Right, KeyModifiers::NONE)
+        .unwrap();
+    harness
+        .send_key(KeyCode::Right, KeyModifiers::NONE)
+        .unwrap();
+    for _ in 0..3 {
+        harness
+            .send_key(KeyCode::Down, KeyModifiers::SHIFT)
+            .unwrap();
+    }
+
+    execute_action(&mut harness, "sort_lines");
+    harness.assert_screen_contains("Sort lines (d descending");
+    harness.type_text("n").unwrap();
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+
+    assert_eq!(
+        harness.get_buffer_content().unwrap(),
+        "header \n  zeta\nalpha\n9\n10\nfooter\n\n"
+    );
+    assert_eq!(harness.get_selected_text(), "  zeta\nalpha\n9\n10");
+
+    // One undo step brings the original back
+    harness
+        .send_key(KeyCode::Char('z'), KeyModifiers::CONTROL)
+        .unwrap();
+    assert_eq!(harness.get_buffer_content().unwrap(), original);
+}
+
+/// Each cursor's lines are transformed on their own
+#[test]
+fn test_reverse_lines_per_cursor() {
+    let mut harness = EditorTestHarness::new(80, 24).unwrap();
+    let _fixture = harness
+        .load_buffer_from_text("m aa\nc\nb\n\nm aa\nz\ny\n")
+        .unwrap();
+
+    // Select "aa" on both of its lines, then extend both selections down
+    harness
+        .send_key(KeyCode::Right, KeyModifiers::NONE)
+        .unwrap();
+    harness
+        .send_key(KeyCode::Right, KeyModifiers::NONE)
+        .unwrap();
+    for _ in 0..2 {
+        harness
+            .send_key(KeyCode::Right, KeyModifiers::SHIFT)
+            .unwrap();
+    }
+    harness
+        .send_key(KeyCode::Char('d'), KeyModifiers::CONTROL)
+        .unwrap();
+    for _ in 0..2 {
+        harness
+            .send_key(KeyCode::Down, KeyModifiers::SHIFT)
+            .unwrap();
+    }
+    assert_eq!(harness.editor().active_state().cursors.count(), 2);
+
+    execute_action(&mut harness, "reverse_lines");
+    assert_eq!(
+        harness.get_buffer_content().unwrap(),
+        "b\nc\nm aa\n\ny\nz\nm aa\n"
+    );
+    let mut selections = harness.editor().active_state().cursors.selections();
+    selections.sort_by_key(|range| range.start);
+    assert_eq!(selections, vec![0..8, 10..18]);
+}
+
+/// With nothing selected the whole buffer is transformed, after confirming
+#[test]
+fn test_unique_lines_whole_buffer_after_confirming() {
+    let mut harness = EditorTestHarness::new(80, 24).unwrap();
+    let _fixture = harness.load_buffer_from_text("b\na\nb\na\n").unwrap();
+
+    execute_action(&mut harness, "unique_lines");
+    harness.assert_screen_contains("in the whole buffer?");
+    harness.send_key(KeyCode::Esc, KeyModifiers::NONE).unwrap();
+    assert_eq!(harness.get_buffer_content().unwrap(), "b\na\nb\na\n");
+
+    execute_action(&mut harness, "unique_lines");
+    harness.type_text("a").unwrap();
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    assert_eq!(harness.get_buffer_content().unwrap(), "b\na\n");
+}
+
+/// Case conversions change exactly the selected text
+#[test]
+fn test_snake_case_selection() {
+    let mut harness = EditorTestHarness::new(80, 24).unwrap();
+    let _fixture = harness
+        .load_buffer_from_text("let parseHTTPResponse = utf8Decoder;\n")
+        .unwrap();
+
+    for _ in 0..4 {
+        harness
+            .send_key(KeyCode::Right, KeyModifiers::NONE)
+            .unwrap();
+    }
+    harness.send_key(KeyCode::End, KeyModifiers::SHIFT).unwrap();
+    execute_action(&mut harness, "to_snake_case");
+
+    assert_eq!(
+        harness.get_buffer_content().unwrap(),
+        "let parse_http_response = utf8_decoder;\n"
+    );
+    assert_eq!(
+        harness.get_selected_text(),
+        "parse_http_response = utf8_decoder;"
+    );
+}
@@ -1,6 +1,7 @@
 // E2E tests for the theme system
 
 use crate::common::harness::EditorTestHarness;
+use crossterm::event::{KeyCode, KeyModifiers};
 use fresh::config::Config;
 use ratatui::style::Color;
 
@@ -181,7 +182,13 @@ fn test_theme_syntax_highlighting_colors() {
 
 #[test]
 fn test_all_available_themes_can_be_loaded() {
-    let themes = vec!["dark", "light", "high-contrast"];
+    let themes = vec![
+        "dark",
+        "light",
+        "high-contrast",
+        "dark-high-contrast",
+        "light-high-contrast",
+    ];
 
     for theme_name in themes {
         let mut config = Config::default();
@@ -259,3 +266,313 @@ fn test_theme_with_underscore_variant() {
     // Should still load high-contrast theme (accepts both - and _)
     assert_eq!(theme.name, "high-contrast");
 }
+
+/// Screen position of the first occurrence of `text`
+fn find_on_screen(harness: &EditorTestHarness, text: &str) -> (u16, u16) {
+    harness
+        .screen_to_string()
+        .lines()
+        .enumerate()
+        .find_map(|(row, line)| {
+            line.find(text)
+                .map(|byte| (line[..byte].chars().count() as u16, row as u16))
+        })
+        .unwrap_or_else(|| panic!("{text:?} not on screen"))
+}
+
+/// Backgrounds of the gutter and of the text area past the end of the line
+/// showing `text`
+fn line_backgrounds(harness: &EditorTestHarness, text: &str) -> (Option<Color>, Option<Color>) {
+    let (_, y) = find_on_screen(harness, text);
+    (
+        harness.get_cell_style(0, y).unwrap().bg,
+        harness.get_cell_style(40, y).unwrap().bg,
+    )
+}
+
+#[test]
+fn test_transparent_background_leaves_editor_background_to_terminal() {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let path = temp_dir.path().join("test.txt");
+    std::fs::write(&path, "hello\nworld\n").unwrap();
+
+    // The default theme paints black
+    let mut harness = EditorTestHarness::new(80, 24).unwrap();
+    harness.open_file(&path).unwrap();
+    harness.render().unwrap();
+    assert_eq!(
+        line_backgrounds(&harness, "world"),
+        (Some(Color::Black), Some(Color::Black))
+    );
+
+    let mut config = Config::default();
+    config.ui.transparent_background = true;
+    let mut harness = EditorTestHarness::with_config(80, 24, config).unwrap();
+    harness.open_file(&path).unwrap();
+    harness.render().unwrap();
+    assert_eq!(
+        line_backgrounds(&harness, "world"),
+        (Some(Color::Reset), Some(Color::Reset))
+    );
+
+    // A selection keeps its own background
+    harness.send_key(KeyCode::End, KeyModifiers::SHIFT).unwrap();
+    harness.render().unwrap();
+    let (x, y) = find_on_screen(&harness, "hello");
+    let selection_bg = harness.editor().theme().selection_bg;
+    assert_ne!(selection_bg, Color::Reset);
+    assert_eq!(harness.get_cell_style(x, y).unwrap().bg, Some(selection_bg));
+}
+
+#[test]
+fn test_theme_file_terminal_background() {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let themes_dir = temp_dir.path().join("themes");
+    std::fs::create_dir_all(&themes_dir).unwrap();
+
+    let mut theme: serde_json::Value =
+        serde_json::from_str(include_str!("../../themes/dracula.json")).unwrap();
+    theme["name"] = "see-through".into();
+    theme["editor"]["bg"] = serde_json::Value::Null;
+    theme["editor"]["line_number_bg"] = "terminal".into();
+    // Text on a selection must stay readable, so it can't be see-through
+    theme["editor"]["selection_bg"] = serde_json::Value::Null;
+    std::fs::write(
+        themes_dir.join("see-through.json"),
+        serde_json::to_string(&theme).unwrap(),
+    )
+    .unwrap();
+
+    let theme = fresh::view::theme::Theme::from_name_in("see-through", Some(&themes_dir));
+    assert_eq!(theme.name, "see-through");
+    assert_eq!(theme.editor_bg, Color::Reset);
+    assert_eq!(theme.line_number_bg, Color::Reset);
+    assert_eq!(
+        theme.selection_bg,
+        fresh::view::theme::Theme::dark().selection_bg
+    );
+}
+
+/// Write a copy of the dracula theme named "live" with the given active tab background
+fn write_live_theme(path: &std::path::Path, tab_active_bg: [u8; 3]) {
+    let mut theme: serde_json::Value =
+        serde_json::from_str(include_str!("../../themes/dracula.json")).unwrap();
+    theme["name"] = "live".into();
+    theme["ui"]["tab_active_bg"] = serde_json::json!(tab_active_bg);
+    std::fs::write(path, serde_json::to_string_pretty(&theme).unwrap()).unwrap();
+}
+
+/// Let the theme watch see a write and report it once it settled
+fn settle_theme_file(harness: &mut EditorTestHarness) {
+    for _ in 0..2 {
+        harness.advance_time(std::time::Duration::from_millis(600));
+        harness.process_async_and_render().unwrap();
+    }
+}
+
+#[test]
+fn test_theme_file_reloads_on_change() {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let dir_context =
+        fresh::config_io::DirectoryContext::for_testing(&temp_dir.path().join("dirs"));
+    let themes_dir = dir_context.themes_dir();
+    std::fs::create_dir_all(&themes_dir).unwrap();
+    let theme_path = themes_dir.join("live.json");
+    write_live_theme(&theme_path, [189, 147, 249]);
+
+    let mut config = Config::default();
+    config.theme = "live".into();
+    let project_dir = temp_dir.path().join("project");
+    std::fs::create_dir_all(&project_dir).unwrap();
+    let mut harness =
+        EditorTestHarness::with_shared_dir_context(80, 24, config, project_dir, dir_context)
+            .unwrap();
+    harness.render().unwrap();
+    assert_eq!(
+        harness.editor().theme().tab_active_bg,
+        Color::Rgb(189, 147, 249)
+    );
+
+    // A valid edit is applied live and shows up on screen
+    write_live_theme(&theme_path, [10, 120, 30]);
+    settle_theme_file(&mut harness);
+    assert_eq!(
+        harness.editor().theme().tab_active_bg,
+        Color::Rgb(10, 120, 30)
+    );
+    let style = harness.get_cell_style(1, 1).unwrap();
+    assert_eq!(style.bg, Some(Color::Rgb(10, 120, 30)));
+
+    // A broken file is retried once, then the current theme is kept
+    std::fs::write(&theme_path, "{\n  \"name\": \"live\",\n  \"editor\": {\n").unwrap();
+    settle_theme_file(&mut harness);
+    harness.advance_time(std::time::Duration::from_millis(400));
+    harness.process_async_and_render().unwrap();
+    assert_eq!(
+        harness.editor().theme().tab_active_bg,
+        Color::Rgb(10, 120, 30)
+    );
+    assert!(harness
+        .editor()
+        .notifications()
+        .history()
+        .any(|n| n.message.contains("live.json") && n.message.contains("line")));
+
+    // Fixing the file applies it again
+    write_live_theme(&theme_path, [200, 60, 5]);
+    settle_theme_file(&mut harness);
+    assert_eq!(
+        harness.editor().theme().tab_active_bg,
+        Color::Rgb(200, 60, 5)
+    );
+}
+
+/// "Check Theme Contrast" lists the pairs below the contrast they need first
+#[test]
+fn test_check_theme_contrast_lists_failing_pairs() {
+    let mut config = Config::default();
+    config.theme = "dark".into();
+    let mut harness = EditorTestHarness::with_config(120, 40, config).unwrap();
+    harness.editor_mut().show_theme_contrast();
+    harness.render().unwrap();
+
+    harness.assert_screen_contains("Theme contrast: dark");
+    harness.assert_screen_contains("dark-high-contrast");
+    let screen = harness.screen_to_string();
+    let failing = screen.find("Too little contrast").unwrap();
+    let passing = screen.find("Enough contrast").unwrap();
+    // Dim line numbers on the dark gutter fall short, with a suggestion
+    let line_numbers = screen.find("editor.line_number_fg").unwrap();
+    assert!(failing < line_numbers && line_numbers < passing, "{screen}");
+    assert!(screen[line_numbers..]
+        .lines()
+        .next()
+        .unwrap()
+        .contains("try #"));
+
+    // The generated variant passes everything
+    let mut config = Config::default();
+    config.theme = "dark-high-contrast".into();
+    let mut harness = EditorTestHarness::with_config(120, 40, config).unwrap();
+    harness.editor_mut().show_theme_contrast();
+    harness.render().unwrap();
+    harness.assert_screen_contains("have the contrast they need");
+    harness.assert_screen_not_contains("Too little contrast");
+}
+
+/// `ui.minimum_contrast` lightens buffer text that falls short as it is drawn
+#[test]
+fn test_minimum_contrast_adjusts_buffer_text() {
+    use fresh::view::theme::contrast_ratio;
+
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let path = temp_dir.path().join("test.txt");
+    std::fs::write(&path, "hello\nworld\n").unwrap();
+
+    let mut config = Config::default();
+    config.theme = "dark".into();
+    let mut harness = EditorTestHarness::with_config(80, 24, config.clone()).unwrap();
+    harness.open_file(&path).unwrap();
+    harness.render().unwrap();
+    let (x, y) = find_on_screen(&harness, "world");
+    let theme = harness.editor().theme().clone();
+    assert_eq!(
+        harness.get_cell_style(x, y).unwrap().fg,
+        Some(theme.editor_fg)
+    );
+    let gutter = harness
+        .screen_to_string()
+        .lines()
+        .nth(y as usize)
+        .unwrap()
+        .chars()
+        .position(|c| c == '2')
+        .unwrap() as u16;
+    let gutter_fg = harness.get_cell_style(gutter, y).unwrap().fg;
+
+    // The dark theme's text is about 11:1; asking for 15:1 lightens it
+    config.ui.minimum_contrast = Some(15.0);
+    let mut harness = EditorTestHarness::with_config(80, 24, config).unwrap();
+    harness.open_file(&path).unwrap();
+    harness.render().unwrap();
+    let fg = harness.get_cell_style(x, y).unwrap().fg.unwrap();
+    assert_ne!(fg, theme.editor_fg);
+    assert!(contrast_ratio(fg, theme.editor_bg).unwrap() >= 15.0);
+
+    // The gutter keeps the theme's colors
+    assert_eq!(harness.get_cell_style(gutter, y).unwrap().fg, gutter_fg);
+}
+
+fn toggle_light_dark(harness: &mut EditorTestHarness) {
+    harness
+        .send_key(KeyCode::Char('p'), KeyModifiers::CONTROL)
+        .unwrap();
+    harness.type_text("Toggle Light/Dark Theme").unwrap();
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+}
+
+/// "Toggle Light/Dark Theme" switches between `ui.light_theme` and
+/// `ui.dark_theme` for the session
+#[test]
+fn test_toggle_light_dark_theme() {
+    let mut config = Config::default();
+    config.theme = "dark".into();
+    config.ui.light_theme = Some("light-high-contrast".into());
+    let mut harness = EditorTestHarness::with_config(80, 24, config).unwrap();
+
+    toggle_light_dark(&mut harness);
+    assert_eq!(harness.editor().theme().name, "light-high-contrast");
+    assert!(harness
+        .editor()
+        .get_status_message()
+        .unwrap()
+        .contains("light-high-contrast"));
+
+    toggle_light_dark(&mut harness);
+    assert_eq!(harness.editor().theme().name, "dark");
+    // The configured theme is left as it is
+    assert_eq!(harness.editor().config().theme.0, "dark");
+}
+
+/// With `theme = "auto"` a change of the terminal's background switches
+/// the theme, and a toggle holds until the background changes again
+#[test]
+fn test_auto_theme_switches_on_terminal_background() {
+    use fresh::view::theme::Appearance;
+
+    let mut config = Config::default();
+    config.theme = "auto".into();
+    let mut harness = EditorTestHarness::with_config(80, 24, config).unwrap();
+    assert_eq!(harness.editor().theme().name, "dark");
+
+    let editor = harness.editor_mut();
+    assert!(!editor.handle_terminal_background(Appearance::Dark));
+    assert!(editor.handle_terminal_background(Appearance::Light));
+    assert_eq!(editor.theme().name, "light");
+
+    toggle_light_dark(&mut harness);
+    assert_eq!(harness.editor().theme().name, "dark");
+    let editor = harness.editor_mut();
+    assert!(!editor.handle_terminal_background(Appearance::Light));
+    assert_eq!(editor.theme().name, "dark");
+    assert!(!editor.handle_terminal_background(Appearance::Dark));
+    assert!(editor.handle_terminal_background(Appearance::Light));
+    assert_eq!(editor.theme().name, "light");
+}
+
+/// A theme that isn't "auto" stays whatever the terminal's background
+#[test]
+fn test_fixed_theme_ignores_terminal_background() {
+    use fresh::view::theme::Appearance;
+
+    let mut config = Config::default();
+    config.theme = "dark".into();
+    let mut harness = EditorTestHarness::with_config(80, 24, config).unwrap();
+
+    let editor = harness.editor_mut();
+    assert!(!editor.handle_terminal_background(Appearance::Light));
+    assert!(!editor.handle_terminal_background(Appearance::Dark));
+    assert_eq!(editor.theme().name, "dark");
+}
@@ -0,0 +1,53 @@
+use crate::common::harness::EditorTestHarness;
+use crossterm::event::{KeyCode, KeyModifiers};
+
+const SOURCE: &str =
+    "fn main() {\n    let s = \"TODO not a comment\";\n    // FIXME(bob): handle errors\n}\n";
+
+/// Only keywords inside comments are listed, and Enter jumps to them
+#[test]
+fn test_todos_list_and_jump() {
+    let mut harness = EditorTestHarness::with_temp_project(100, 30).unwrap();
+    let project_root = harness.project_dir().unwrap();
+    let file = project_root.join("main.rs");
+    std::fs::write(&file, SOURCE).unwrap();
+
+    harness.editor_mut().list_todos();
+    harness
+        .wait_until(|h| h.screen_to_string().contains("handle errors"))
+        .unwrap();
+    harness.assert_screen_contains("main.rs (1)");
+    harness.assert_screen_contains("FIXME  (bob) handle errors");
+    harness.assert_screen_not_contains("not a comment");
+
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    harness.render().unwrap();
+    assert_eq!(
+        harness.editor().active_state().buffer.file_path(),
+        Some(file.as_path())
+    );
+    assert_eq!(harness.cursor_position(), SOURCE.find("FIXME").unwrap());
+}
+
+/// Keywords in comments of open buffers get a highlight, others don't
+#[test]
+fn test_todos_highlight_in_comments() {
+    let mut harness = EditorTestHarness::with_temp_project(100, 30).unwrap();
+    let file = harness.project_dir().unwrap().join("main.rs");
+    std::fs::write(&file, SOURCE).unwrap();
+    harness.open_file(&file).unwrap();
+    harness.render().unwrap();
+
+    let state = harness.editor().active_state();
+    let highlighted: Vec<_> = state
+        .overlays
+        .all()
+        .iter()
+        .filter(|o| o.namespace.as_ref().map(|n| n.as_str()) == Some("todo-keywords"))
+        .map(|o| o.range(&state.marker_list))
+        .collect();
+    let fixme = SOURCE.find("FIXME").unwrap();
+    assert_eq!(highlighted, vec![fixme..fixme + "FIXME".len()]);
+}
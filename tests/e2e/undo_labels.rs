@@ -0,0 +1,99 @@
+//! E2E tests for the names undo and redo give compound edits
+use crate::common::harness::EditorTestHarness;
+use crossterm::event::{KeyCode, KeyModifiers};
+use fresh::services::plugins::api::PluginCommand;
+
+fn status(harness: &EditorTestHarness) -> String {
+    harness
+        .editor()
+        .get_status_message()
+        .cloned()
+        .unwrap_or_default()
+}
+
+fn undo(harness: &mut EditorTestHarness) {
+    harness
+        .send_key(KeyCode::Char('z'), KeyModifiers::CONTROL)
+        .unwrap();
+}
+
+fn redo(harness: &mut EditorTestHarness) {
+    harness
+        .send_key(KeyCode::Char('y'), KeyModifiers::CONTROL)
+        .unwrap();
+}
+
+/// Undoing and redoing a replace-all names it with its count
+#[test]
+fn test_replace_all_named_in_undo_and_redo() {
+    let mut harness = EditorTestHarness::new(80, 24).unwrap();
+    harness.type_text("foo bar foo\nfoo").unwrap();
+
+    harness
+        .send_key(KeyCode::Char('r'), KeyModifiers::CONTROL)
+        .unwrap();
+    harness.type_text("foo").unwrap();
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    harness.type_text("baz").unwrap();
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    harness.assert_buffer_content("baz bar baz\nbaz");
+
+    undo(&mut harness);
+    harness.assert_buffer_content("foo bar foo\nfoo");
+    assert_eq!(status(&harness), "Undid: Replace 3 occurrence(s)");
+
+    redo(&mut harness);
+    harness.assert_buffer_content("baz bar baz\nbaz");
+    assert_eq!(status(&harness), "Redid: Replace 3 occurrence(s)");
+}
+
+/// A paste is named with its line count; typing is undone without a name
+#[test]
+fn test_paste_named_and_typing_unnamed() {
+    let mut harness = EditorTestHarness::new(80, 24).unwrap();
+    harness.type_text("x").unwrap();
+    harness
+        .editor_mut()
+        .set_clipboard_for_test("a\nb\nc".to_string());
+    harness.editor_mut().paste_for_test();
+    harness.assert_buffer_content("xa\nb\nc");
+
+    undo(&mut harness);
+    harness.assert_buffer_content("x");
+    assert_eq!(status(&harness), "Undid: Paste 3 line(s)");
+
+    harness.editor_mut().set_status_message("ready".to_string());
+    undo(&mut harness);
+    harness.assert_buffer_content("");
+    assert!(
+        !status(&harness).starts_with("Undid"),
+        "{}",
+        status(&harness)
+    );
+}
+
+/// A plugin names its `applyEdits` step
+#[test]
+fn test_plugin_edits_named_by_their_label() {
+    let mut harness = EditorTestHarness::new(80, 24).unwrap();
+    harness.type_text("a;b;").unwrap();
+    let buffer_id = harness.editor().active_buffer();
+    harness
+        .editor_mut()
+        .handle_plugin_command(PluginCommand::ApplyEdits {
+            buffer_id,
+            edits: vec![(1..2, String::new()), (3..4, String::new())],
+            label: Some("Remove semicolons".to_string()),
+        })
+        .unwrap();
+    harness.render().unwrap();
+    harness.assert_buffer_content("ab");
+
+    undo(&mut harness);
+    harness.assert_buffer_content("a;b;");
+    assert_eq!(status(&harness), "Undid: Remove semicolons");
+}
@@ -0,0 +1,84 @@
+//! E2E tests for undo/redo around plugin edits
+use crate::common::harness::EditorTestHarness;
+use crossterm::event::{KeyCode, KeyModifiers};
+use fresh::config::Config;
+use fresh::model::event::{CursorId, Event};
+use fresh::services::plugins::api::PluginCommand;
+
+fn harness_with(undo_skips_plugin_edits: bool) -> EditorTestHarness {
+    let mut config = Config::default();
+    config.editor.undo_skips_plugin_edits = undo_skips_plugin_edits;
+    EditorTestHarness::with_config(80, 24, config).unwrap()
+}
+
+/// Type "ab", then let a "plugin" insert "X" at the start of the buffer
+fn type_then_plugin_insert(harness: &mut EditorTestHarness) {
+    harness.type_text("ab").unwrap();
+    let buffer_id = harness.editor().active_buffer();
+    harness
+        .editor_mut()
+        .handle_plugin_command(PluginCommand::InsertText {
+            buffer_id,
+            position: 0,
+            text: "X".to_string(),
+        })
+        .unwrap();
+    harness.render().unwrap();
+    harness.assert_buffer_content("Xab");
+}
+
+#[test]
+fn test_undo_reverts_plugin_edit_in_chronological_mode() {
+    let mut harness = harness_with(false);
+    type_then_plugin_insert(&mut harness);
+
+    harness
+        .send_key(KeyCode::Char('z'), KeyModifiers::CONTROL)
+        .unwrap();
+    harness.assert_buffer_content("ab");
+}
+
+#[test]
+fn test_undo_skips_plugin_edits_when_enabled() {
+    let mut harness = harness_with(true);
+    type_then_plugin_insert(&mut harness);
+
+    harness
+        .send_key(KeyCode::Char('z'), KeyModifiers::CONTROL)
+        .unwrap();
+    harness.assert_buffer_content("a");
+
+    harness
+        .send_key(KeyCode::Char('y'), KeyModifiers::CONTROL)
+        .unwrap();
+    harness.assert_buffer_content("Xab");
+}
+
+#[test]
+fn test_redo_drops_entries_that_no_longer_fit() {
+    let mut harness = harness_with(false);
+    harness.type_text("abc").unwrap();
+    harness
+        .send_key(KeyCode::Char('z'), KeyModifiers::CONTROL)
+        .unwrap();
+    harness.assert_buffer_content("ab");
+
+    // Change the buffer behind the event log's back
+    harness
+        .editor_mut()
+        .active_state_mut()
+        .apply(&Event::Delete {
+            range: 0..2,
+            deleted_text: "ab".to_string(),
+            cursor_id: CursorId(0),
+        });
+
+    harness
+        .send_key(KeyCode::Char('y'), KeyModifiers::CONTROL)
+        .unwrap();
+    harness.assert_buffer_content("");
+    assert_eq!(
+        harness.editor().get_status_message().map(String::as_str),
+        Some("Redo history no longer matches the buffer and was discarded")
+    );
+}
@@ -1,6 +1,8 @@
 //! E2E tests for the update notification UI
 
 use crate::common::harness::EditorTestHarness;
+use crossterm::event::{KeyCode, KeyModifiers};
+use fresh::config::Config;
 use fresh::services::release_checker::{
     start_periodic_update_check_with_interval, CURRENT_VERSION,
 };
@@ -69,7 +71,12 @@ fn test_update_notification_appears_in_status_bar() {
     let mut harness = EditorTestHarness::new(100, 24).unwrap();
 
     // Create an update checker pointing to our mock server
-    let checker = start_periodic_update_check_with_interval(&url, Duration::from_secs(3600));
+    let checker = start_periodic_update_check_with_interval(
+        harness.editor().worker_pool(),
+        &url,
+        Duration::from_secs(3600),
+        harness.editor().time_source().clone(),
+    );
 
     // Inject the checker into the editor
     harness.editor_mut().set_update_checker(checker);
@@ -120,7 +127,12 @@ fn test_update_notification_not_shown_when_current() {
 
     let mut harness = EditorTestHarness::new(100, 24).unwrap();
 
-    let checker = start_periodic_update_check_with_interval(&url, Duration::from_secs(3600));
+    let checker = start_periodic_update_check_with_interval(
+        harness.editor().worker_pool(),
+        &url,
+        Duration::from_secs(3600),
+        harness.editor().time_source().clone(),
+    );
     harness.editor_mut().set_update_checker(checker);
 
     // Wait for the update check to complete
@@ -165,7 +177,12 @@ fn test_update_notification_positioned_near_ctrl_p() {
 
     let mut harness = EditorTestHarness::new(120, 24).unwrap();
 
-    let checker = start_periodic_update_check_with_interval(&url, Duration::from_secs(3600));
+    let checker = start_periodic_update_check_with_interval(
+        harness.editor().worker_pool(),
+        &url,
+        Duration::from_secs(3600),
+        harness.editor().time_source().clone(),
+    );
     harness.editor_mut().set_update_checker(checker);
 
     // Wait for update check
@@ -208,3 +225,30 @@ fn test_update_notification_positioned_near_ctrl_p() {
     drop(harness);
     let _ = stop_tx.send(());
 }
+
+/// Run "Update Now" from the command palette
+fn run_update_now(harness: &mut EditorTestHarness) {
+    harness
+        .send_key(KeyCode::Char('p'), KeyModifiers::CONTROL)
+        .unwrap();
+    harness.type_text("Update Now").unwrap();
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    harness.render().unwrap();
+}
+
+#[test]
+fn test_update_now_needs_opt_in_and_a_newer_release() {
+    // Off by default: nothing is downloaded
+    let mut harness = EditorTestHarness::new(120, 24).unwrap();
+    run_update_now(&mut harness);
+    assert!(harness.get_status_bar().contains("Self-update is off"));
+
+    // On, but no release check found anything newer
+    let mut config = Config::default();
+    config.updates.self_update = true;
+    let mut harness = EditorTestHarness::with_config(120, 24, config).unwrap();
+    run_update_now(&mut harness);
+    assert!(harness.get_status_bar().contains("No newer release found"));
+}
@@ -0,0 +1,175 @@
+use crate::common::harness::{EditorTestHarness, HarnessOptions};
+use crossterm::event::{KeyCode, KeyModifiers};
+use fresh::config::Config;
+use fresh::config_io::DirectoryContext;
+use fresh::services::usage_stats::UsageStats;
+use std::time::Duration;
+use tempfile::TempDir;
+
+/// Harness with its own state directory and a project holding `main.rs`
+fn stats_harness(enabled: bool) -> (EditorTestHarness, DirectoryContext, TempDir) {
+    let state_temp = TempDir::new().unwrap();
+    let dir_context = DirectoryContext::for_testing(state_temp.path());
+    let mut config = Config::default();
+    config.stats.enabled = enabled;
+    let harness = EditorTestHarness::create(
+        100,
+        24,
+        HarnessOptions::new()
+            .with_config(config)
+            .with_project_root()
+            .with_shared_dir_context(dir_context.clone()),
+    )
+    .unwrap();
+    std::fs::write(
+        harness.project_dir().unwrap().join("main.rs"),
+        "fn main() {}\n",
+    )
+    .unwrap();
+    (harness, dir_context, state_temp)
+}
+
+/// A short session: open a file, type, move, and switch away for a while
+fn scripted_session(harness: &mut EditorTestHarness) {
+    let file = harness.project_dir().unwrap().join("main.rs");
+    harness.open_file(&file).unwrap();
+    harness.type_text("ab").unwrap();
+    harness.send_key(KeyCode::End, KeyModifiers::NONE).unwrap();
+    harness.send_key(KeyCode::Home, KeyModifiers::NONE).unwrap();
+    harness.send_key(KeyCode::End, KeyModifiers::NONE).unwrap();
+
+    // Focused for 5s, away for 100s, then focused for 3s more
+    harness.advance_time(Duration::from_secs(5));
+    harness.editor_mut().handle_focus_change(false);
+    harness.advance_time(Duration::from_secs(100));
+    harness.editor_mut().handle_focus_change(true);
+    harness.advance_time(Duration::from_secs(3));
+}
+
+/// Commands, keys, focused time and opened files of a session all land in
+/// the statistics file with exact counts
+#[test]
+fn test_usage_stats_count_scripted_session() {
+    let (mut harness, dir_context, _state) = stats_harness(true);
+    scripted_session(&mut harness);
+    harness.editor_mut().flush_usage_stats();
+
+    let stats = UsageStats::load(&dir_context.usage_stats_path());
+    assert_eq!(stats.days.len(), 1);
+    let totals = stats.totals();
+    assert_eq!(totals.keys, 5);
+    assert_eq!(totals.focused_secs, 8);
+    assert_eq!(totals.commands.get("MoveLineEnd"), Some(&2));
+    assert_eq!(totals.commands.get("MoveLineStart"), Some(&1));
+    // Typing counts as keys, not as commands
+    assert_eq!(totals.commands.values().sum::<u64>(), 3);
+    assert_eq!(totals.languages.get("rust"), Some(&1));
+    assert_eq!(totals.files.len(), 1);
+
+    // A second flush adds only what was counted since
+    harness.send_key(KeyCode::Home, KeyModifiers::NONE).unwrap();
+    harness.editor_mut().flush_usage_stats();
+    let totals = UsageStats::load(&dir_context.usage_stats_path()).totals();
+    assert_eq!(totals.keys, 6);
+    assert_eq!(totals.commands.get("MoveLineStart"), Some(&2));
+}
+
+/// Counts are written once input has paused, not on every key
+#[test]
+fn test_usage_stats_written_in_batches_when_idle() {
+    let (mut harness, dir_context, _state) = stats_harness(true);
+    let path = dir_context.usage_stats_path();
+
+    harness.advance_time(Duration::from_secs(120));
+    harness.type_text("abc").unwrap();
+    harness.editor_mut().flush_usage_stats_when_idle();
+    assert!(!path.exists(), "written while the user is typing");
+
+    harness.advance_time(Duration::from_secs(3));
+    harness.editor_mut().flush_usage_stats_when_idle();
+    assert_eq!(UsageStats::load(&path).totals().keys, 3);
+
+    // The next batch waits for the flush interval
+    harness.type_text("d").unwrap();
+    harness.advance_time(Duration::from_secs(3));
+    harness.editor_mut().flush_usage_stats_when_idle();
+    assert_eq!(UsageStats::load(&path).totals().keys, 3);
+    harness.advance_time(Duration::from_secs(60));
+    harness.editor_mut().flush_usage_stats_when_idle();
+    assert_eq!(UsageStats::load(&path).totals().keys, 4);
+}
+
+/// Days past the retention window are dropped when the file is written
+#[test]
+fn test_usage_stats_prune_days_past_retention() {
+    let (mut harness, dir_context, _state) = stats_harness(true);
+    let path = dir_context.usage_stats_path();
+    let today = chrono::Local::now().date_naive();
+
+    let mut stored = UsageStats::default();
+    stored.record_key(today - chrono::Days::new(400));
+    stored.record_key(today - chrono::Days::new(10));
+    stored.save(&path).unwrap();
+
+    harness.type_text("a").unwrap();
+    harness.editor_mut().flush_usage_stats();
+
+    let stats = UsageStats::load(&path);
+    assert_eq!(stats.days.len(), 2);
+    assert_eq!(stats.totals().keys, 2);
+}
+
+/// With statistics off nothing is written, not even at shutdown
+#[test]
+fn test_usage_stats_disabled_writes_nothing() {
+    let (mut harness, dir_context, _state) = stats_harness(false);
+    scripted_session(&mut harness);
+    harness.advance_time(Duration::from_secs(120));
+    harness.editor_mut().flush_usage_stats_when_idle();
+    harness.editor_mut().flush_usage_stats();
+    harness.editor_mut().show_usage_stats();
+    harness.render().unwrap();
+
+    assert!(!dir_context.usage_stats_path().exists());
+    harness.assert_screen_contains("Usage statistics are off");
+    harness.assert_screen_contains("Nothing counted yet");
+}
+
+/// The report lists the totals, the daily sparkline and the top entries
+#[test]
+fn test_usage_stats_report() {
+    let (mut harness, _dir_context, _state) = stats_harness(true);
+    scripted_session(&mut harness);
+    harness.editor_mut().show_usage_stats();
+    harness.render().unwrap();
+
+    harness.assert_screen_contains("Keys pressed: 5");
+    harness.assert_screen_contains("Focused time: 8s");
+    harness.assert_screen_contains("Keys per day");
+    harness.assert_screen_contains("Top commands");
+    harness.assert_screen_contains("MoveLineEnd");
+    harness.assert_screen_contains("main.rs");
+    harness.assert_screen_contains("rust");
+}
+
+/// "Export Usage Statistics" writes the counts as JSON to the chosen file
+#[test]
+fn test_usage_stats_export_to_json() {
+    let (mut harness, _dir_context, _state) = stats_harness(true);
+    scripted_session(&mut harness);
+    harness.editor_mut().start_export_usage_stats_prompt();
+    harness.render().unwrap();
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+
+    let exported = harness.project_dir().unwrap().join("usage-stats.json");
+    let json = std::fs::read_to_string(&exported).unwrap();
+    let stats: UsageStats = serde_json::from_str(&json).unwrap();
+    assert_eq!(stats.totals().commands.get("MoveLineEnd"), Some(&2));
+    assert!(harness
+        .editor()
+        .get_status_message()
+        .unwrap()
+        .contains("Usage statistics exported"));
+}
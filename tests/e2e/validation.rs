@@ -0,0 +1,57 @@
+//! E2E tests for the invariant checks the harness runs after every event
+
+use crate::common::harness::EditorTestHarness;
+use crossterm::event::{KeyCode, KeyModifiers};
+use fresh::view::virtual_text::VirtualTextPosition;
+use ratatui::style::Style;
+
+/// Editing, splitting and closing keep the model valid
+#[test]
+fn test_validation_passes_through_editing_and_splits() {
+    let mut harness = EditorTestHarness::new(80, 24).unwrap();
+    harness.type_text("héllo\nwörld").unwrap();
+    harness
+        .send_key(KeyCode::Left, KeyModifiers::SHIFT)
+        .unwrap();
+    harness
+        .send_key(KeyCode::Backspace, KeyModifiers::NONE)
+        .unwrap();
+    harness.editor_mut().split_pane_horizontal();
+    harness.type_text("ü").unwrap();
+    harness.editor_mut().close_active_split();
+    harness.editor().validate("test");
+    harness.assert_buffer_content("ühéllo\nwörl");
+}
+
+/// A cursor outside the buffer is reported by validate
+#[test]
+#[should_panic(expected = "Editor invariant violated after test")]
+fn test_validation_catches_cursor_past_end() {
+    let mut harness = EditorTestHarness::new(80, 24).unwrap();
+    harness.type_text("hello").unwrap();
+    harness
+        .editor_mut()
+        .active_state_mut()
+        .cursors
+        .primary_mut()
+        .position = 100;
+    harness.editor().validate("test");
+}
+
+/// A corruption is reported at the key event that follows it
+#[test]
+#[should_panic(expected = "after key event: buffer")]
+fn test_validation_runs_after_key_events() {
+    let mut harness = EditorTestHarness::new(80, 24).unwrap();
+    harness.type_text("hello").unwrap();
+    let state = harness.editor_mut().active_state_mut();
+    state.virtual_texts.add(
+        &mut state.marker_list,
+        100,
+        "hint".to_string(),
+        Style::default(),
+        VirtualTextPosition::AfterChar,
+        0,
+    );
+    harness.send_key(KeyCode::Left, KeyModifiers::NONE).unwrap();
+}
@@ -5,9 +5,9 @@
 use crate::common::harness::EditorTestHarness;
 use crate::common::visual_testing::VisualFlow;
 use crossterm::event::{KeyCode, KeyModifiers};
-use fresh::model::event::{Event, OverlayFace};
-use fresh::view::overlay::OverlayNamespace;
-use ratatui::style::Color;
+use fresh::services::async_bridge::AsyncMessage;
+use fresh::services::lsp::uri::path_to_uri;
+use lsp_types::{Diagnostic, DiagnosticSeverity, Position, Range};
 use std::fs;
 
 /// Comprehensive visual test A:
@@ -98,30 +98,34 @@ fn long_function() {
     harness.send_key(KeyCode::Esc, KeyModifiers::NONE).unwrap();
     harness.render().unwrap();
 
-    // Add LSP diagnostic markers (simulated)
-    {
-        let state = harness.editor_mut().active_state_mut();
-
-        // Add diagnostic overlay for "unused_var" on line 11
-        state.apply(&Event::AddOverlay {
-            namespace: Some(OverlayNamespace::from_string("lsp-diagnostic".to_string())),
-            range: 230..240,
-            face: OverlayFace::Background {
-                color: (60, 20, 20),
-            },
-            priority: 100,
-            message: Some("unused variable: `unused_var`".to_string()),
-            extend_to_line_end: false,
-        });
-
-        // Add margin indicators
-        state
-            .margins
-            .set_diagnostic_indicator(10, "●".to_string(), Color::Red);
-        state
-            .margins
-            .set_diagnostic_indicator(11, "●".to_string(), Color::Yellow);
-    }
+    // Publish LSP diagnostics for the unused variables, as a server would
+    let uri = path_to_uri(&project_dir.join("src/main.rs")).unwrap();
+    harness
+        .editor()
+        .async_bridge()
+        .unwrap()
+        .sender()
+        .send(AsyncMessage::LspDiagnostics {
+            language: "rust".to_string(),
+            uri: uri.as_str().to_string(),
+            diagnostics: vec![
+                Diagnostic {
+                    range: Range::new(Position::new(10, 8), Position::new(10, 18)),
+                    severity: Some(DiagnosticSeverity::ERROR),
+                    message: "unused variable: `unused_var`".to_string(),
+                    ..Default::default()
+                },
+                Diagnostic {
+                    range: Range::new(Position::new(11, 8), Position::new(11, 22)),
+                    severity: Some(DiagnosticSeverity::WARNING),
+                    message: "unused variable: `another_unused`".to_string(),
+                    ..Default::default()
+                },
+            ],
+        })
+        .unwrap();
+    harness.editor_mut().process_async_messages();
+    harness.render().unwrap();
 
     // Scroll down a bit to show scrolled state
     for _ in 0..5 {
@@ -0,0 +1,161 @@
+//! Tests for the winbar, the line at the top of each split
+
+use crate::common::harness::EditorTestHarness;
+use crossterm::event::{KeyCode, KeyModifiers};
+use fresh::config::{Config, WinbarVisibility};
+use fresh::services::plugins::api::PluginCommand;
+use fresh::view::glyphs::glyphs;
+use tempfile::TempDir;
+
+/// Run a command from the command palette
+fn run_command(harness: &mut EditorTestHarness, name: &str) {
+    harness
+        .send_key(KeyCode::Char('p'), KeyModifiers::CONTROL)
+        .unwrap();
+    harness.type_text(name).unwrap();
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    harness.render().unwrap();
+}
+
+/// Harness of `width` columns showing `content` from `main.rs`
+fn open(width: u16, config: Config, content: &str) -> (EditorTestHarness, TempDir) {
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join("main.rs");
+    std::fs::write(&path, content).unwrap();
+    let mut harness = EditorTestHarness::with_config(width, 24, config).unwrap();
+    harness.open_file(&path).unwrap();
+    harness.render().unwrap();
+    (harness, temp_dir)
+}
+
+fn winbar_config(winbar: WinbarVisibility) -> Config {
+    let mut config = Config::default();
+    config.ui.winbar = winbar;
+    config
+}
+
+#[test]
+fn test_winbar_shows_while_splits_are_visible() {
+    let (mut harness, _temp_dir) = open(100, Config::default(), "fn main() {}\n");
+    let (first_row, _) = harness.content_area_rows();
+
+    // One split: the text starts right below the tabs
+    assert!(harness.get_screen_row(first_row).contains("fn main()"));
+
+    // Two splits: each gets a winbar naming its buffer, above the text
+    run_command(&mut harness, "Split Vertical");
+    let winbar = harness.get_screen_row(first_row);
+    assert_eq!(winbar.matches("main.rs").count(), 2, "{winbar}");
+    assert!(!winbar.contains("fn main()"));
+    assert!(harness.get_screen_row(first_row + 1).contains("fn main()"));
+
+    // Unsaved changes are marked in both, as the splits share the buffer
+    harness.type_text("x").unwrap();
+    harness.render().unwrap();
+    let winbar = harness.get_screen_row(first_row);
+    let marked = format!("main.rs {}", glyphs().modified);
+    assert_eq!(winbar.matches(&marked).count(), 2, "{winbar}");
+
+    // Back to one split, the winbar goes away
+    run_command(&mut harness, "Close Split");
+    assert!(harness.get_screen_row(first_row).contains("main()"));
+}
+
+#[test]
+fn test_winbar_always_and_never() {
+    let (harness, _temp_dir) = open(
+        100,
+        winbar_config(WinbarVisibility::Always),
+        "fn main() {}\n",
+    );
+    let (first_row, _) = harness.content_area_rows();
+    assert!(harness.get_screen_row(first_row).contains("main.rs"));
+    assert!(harness.get_screen_row(first_row + 1).contains("fn main()"));
+
+    let (mut harness, _temp_dir) = open(
+        100,
+        winbar_config(WinbarVisibility::Never),
+        "fn main() {}\n",
+    );
+    run_command(&mut harness, "Split Vertical");
+    let row = harness.get_screen_row(first_row);
+    assert_eq!(row.matches("fn main()").count(), 2, "{row}");
+}
+
+#[test]
+fn test_winbar_click_focuses_split_and_text_clicks_skip_it() {
+    let text = "first line\nsecond line\n";
+    let (mut harness, _temp_dir) = open(100, Config::default(), text);
+    let (first_row, _) = harness.content_area_rows();
+    let left = harness.editor().get_active_split();
+
+    run_command(&mut harness, "Split Vertical");
+    let right = harness.editor().get_active_split();
+    assert_ne!(left, right);
+
+    // Clicking the left split's winbar focuses it without moving its cursor
+    harness.mouse_click(5, first_row as u16).unwrap();
+    harness.render().unwrap();
+    assert_eq!(harness.editor().get_active_split(), left);
+    assert_eq!(harness.cursor_position(), 0);
+
+    // The second line of text is two rows below the winbar
+    let row = harness.get_screen_row(first_row + 2);
+    let col = row[..row.find("second").unwrap()].chars().count();
+    harness
+        .mouse_click(col as u16, first_row as u16 + 2)
+        .unwrap();
+    harness.render().unwrap();
+    assert_eq!(harness.cursor_position(), text.find("second").unwrap());
+}
+
+#[test]
+fn test_plugin_winbar_segments_by_priority() {
+    let (mut harness, _temp_dir) = open(40, winbar_config(WinbarVisibility::Always), "");
+    let (first_row, _) = harness.content_area_rows();
+    let split_id = harness.editor().get_active_split();
+    let set_segment = |harness: &mut EditorTestHarness, id: &str, text: &str, priority: i32| {
+        harness
+            .editor_mut()
+            .handle_plugin_command(PluginCommand::SetWinbarSegment {
+                split_id,
+                id: id.to_string(),
+                text: text.to_string(),
+                color: None,
+                priority,
+            })
+            .unwrap();
+        harness.render().unwrap();
+    };
+
+    set_segment(&mut harness, "branch", "on-main", 5);
+    let winbar = harness.get_screen_row(first_row);
+    assert!(winbar.contains("main.rs") && winbar.trim_end().ends_with("on-main"));
+
+    // Too wide for both: the lower priority segment is dropped
+    let long = "x".repeat(25);
+    set_segment(&mut harness, "lint", &long, 1);
+    let winbar = harness.get_screen_row(first_row);
+    assert!(
+        winbar.contains("on-main") && !winbar.contains("xxx"),
+        "{winbar}"
+    );
+
+    // Removing the other segment makes room for it
+    set_segment(&mut harness, "branch", "", 5);
+    let winbar = harness.get_screen_row(first_row);
+    assert!(
+        winbar.contains(&long) && !winbar.contains("on-main"),
+        "{winbar}"
+    );
+
+    // Setting a segment again replaces it
+    set_segment(&mut harness, "lint", "lint: ok", 1);
+    let winbar = harness.get_screen_row(first_row);
+    assert!(
+        winbar.contains("lint: ok") && !winbar.contains("xxx"),
+        "{winbar}"
+    );
+}
@@ -0,0 +1,80 @@
+use crate::common::harness::EditorTestHarness;
+use crossterm::event::{KeyCode, KeyModifiers};
+use fresh::config::{Config, WordCompletionScope};
+use std::time::Duration;
+
+/// Let the editor sit idle long enough to index open buffers
+fn index_while_idle(harness: &mut EditorTestHarness) {
+    harness.advance_time(Duration::from_millis(300));
+    for _ in 0..10 {
+        harness.editor_mut().index_words_when_idle();
+    }
+}
+
+/// Without a language server, completion offers words of the buffer, most
+/// frequent first, and accepting one replaces the typed prefix
+#[test]
+fn test_word_completion_without_lsp() {
+    let mut harness = EditorTestHarness::with_temp_project(80, 24).unwrap();
+    let file = harness.project_dir().unwrap().join("notes.txt");
+    std::fs::write(&file, "compare compute_total compute_total\n").unwrap();
+    harness.open_file(&file).unwrap();
+    harness
+        .send_key(KeyCode::End, KeyModifiers::CONTROL)
+        .unwrap();
+    harness.type_text("comp").unwrap();
+
+    index_while_idle(&mut harness);
+    harness
+        .send_key(KeyCode::Char(' '), KeyModifiers::CONTROL)
+        .unwrap();
+    harness.render().unwrap();
+    let selected = harness
+        .editor()
+        .active_state()
+        .popups
+        .top()
+        .and_then(|popup| popup.selected_item())
+        .map(|item| item.text.clone());
+    assert_eq!(selected.as_deref(), Some("compute_total"));
+    harness.assert_screen_contains("compare");
+
+    harness
+        .send_key(KeyCode::Enter, KeyModifiers::NONE)
+        .unwrap();
+    assert_eq!(
+        harness.get_buffer_content().unwrap(),
+        "compare compute_total compute_total\ncompute_total"
+    );
+}
+
+/// Words of other buffers are only offered when all buffers are indexed
+#[test]
+fn test_word_completion_scope() {
+    for (scope, offered) in [
+        (WordCompletionScope::AllBuffers, true),
+        (WordCompletionScope::CurrentBuffer, false),
+    ] {
+        let mut config = Config::default();
+        config.editor.word_completion = scope;
+        let mut harness = EditorTestHarness::with_temp_project_and_config(80, 24, config).unwrap();
+        let root = harness.project_dir().unwrap();
+        std::fs::write(root.join("other.txt"), "elsewhere_word\n").unwrap();
+        std::fs::write(root.join("main.txt"), "").unwrap();
+        harness.open_file(&root.join("other.txt")).unwrap();
+        harness.open_file(&root.join("main.txt")).unwrap();
+        harness.type_text("else").unwrap();
+
+        index_while_idle(&mut harness);
+        harness
+            .send_key(KeyCode::Char(' '), KeyModifiers::CONTROL)
+            .unwrap();
+        harness.render().unwrap();
+        assert_eq!(
+            harness.screen_to_string().contains("elsewhere_word"),
+            offered,
+            "{:?}",
+            scope
+        );
+    }
+}
@@ -0,0 +1,178 @@
+//! E2E tests for the diagnostics store and the workspace diagnostics panel
+
+use crate::common::harness::EditorTestHarness;
+use crossterm::event::{KeyCode, KeyModifiers};
+use fresh::services::async_bridge::AsyncMessage;
+use fresh::services::lsp::uri::path_to_uri;
+use lsp_types::{Diagnostic, DiagnosticSeverity, Position, Range};
+use std::path::Path;
+
+fn diagnostic(line: u32, severity: DiagnosticSeverity, message: &str) -> Diagnostic {
+    Diagnostic {
+        range: Range::new(Position::new(line, 4), Position::new(line, 5)),
+        severity: Some(severity),
+        message: message.to_string(),
+        ..Default::default()
+    }
+}
+
+/// Publish `diagnostics` for `uri` as a language server would
+fn publish(harness: &mut EditorTestHarness, uri: &str, diagnostics: Vec<Diagnostic>) {
+    harness
+        .editor()
+        .async_bridge()
+        .unwrap()
+        .sender()
+        .send(AsyncMessage::LspDiagnostics {
+            language: "rust".to_string(),
+            uri: uri.to_string(),
+            diagnostics,
+        })
+        .unwrap();
+    harness.editor_mut().process_async_messages();
+    harness.render().unwrap();
+}
+
+fn uri_of(path: &Path) -> String {
+    path_to_uri(path).unwrap().as_str().to_string()
+}
+
+fn press(harness: &mut EditorTestHarness, code: KeyCode, times: usize) {
+    for _ in 0..times {
+        harness.send_key(code, KeyModifiers::NONE).unwrap();
+    }
+    harness.render().unwrap();
+}
+
+/// Publications spelling one file's URI differently replace each other in
+/// a single entry, and the status bar counts what the last one sent
+#[test]
+fn test_mixed_uri_encodings_merge_into_one_entry() {
+    let mut harness = EditorTestHarness::with_temp_project(100, 30).unwrap();
+    let file = harness.project_dir().unwrap().join("my notes.txt");
+    std::fs::write(&file, "let x = 1;\nlet y = 2;\n").unwrap();
+    harness.open_file(&file).unwrap();
+    harness.render().unwrap();
+
+    let encoded = uri_of(&file);
+    assert!(encoded.contains("my%20notes.txt"));
+    publish(
+        &mut harness,
+        &encoded,
+        vec![
+            diagnostic(0, DiagnosticSeverity::ERROR, "first"),
+            diagnostic(1, DiagnosticSeverity::ERROR, "second"),
+        ],
+    );
+    harness.assert_screen_contains("E:2");
+
+    // The same file, unescaped and through a `.` component
+    let dir = file.parent().unwrap().to_string_lossy().to_string();
+    let raw = format!("file://{}/./my notes.txt", dir);
+    publish(
+        &mut harness,
+        &raw,
+        vec![
+            diagnostic(0, DiagnosticSeverity::ERROR, "first"),
+            diagnostic(1, DiagnosticSeverity::WARNING, "second"),
+        ],
+    );
+
+    let store = harness.editor().get_stored_diagnostics();
+    assert_eq!(store.len(), 1);
+    let entry = store.for_path(&file).unwrap();
+    assert_eq!(entry.uri, encoded);
+    assert_eq!((entry.counts.errors, entry.counts.warnings), (1, 1));
+    assert_eq!(store.summary().total.errors, 1);
+    harness.assert_screen_contains("E:1 W:1");
+    harness.assert_screen_not_contains("E:2");
+
+    // An empty publication under yet another spelling clears it
+    publish(
+        &mut harness,
+        &raw.replace("file://", "file://localhost"),
+        vec![],
+    );
+    assert!(harness.editor().get_stored_diagnostics().is_empty());
+    harness.assert_screen_not_contains("E:1");
+}
+
+/// The panel lists files by errors, expands them on Enter, jumps to a
+/// diagnostic, and follows new publications
+#[test]
+fn test_workspace_diagnostics_panel() {
+    let mut harness = EditorTestHarness::with_temp_project(100, 30).unwrap();
+    let project_root = harness.project_dir().unwrap();
+    let quiet = project_root.join("quiet.txt");
+    let broken = project_root.join("broken.txt");
+    std::fs::write(&quiet, "one\n").unwrap();
+    std::fs::write(&broken, "line one\nline two\nline three\n").unwrap();
+
+    publish(
+        &mut harness,
+        &uri_of(&quiet),
+        vec![diagnostic(0, DiagnosticSeverity::WARNING, "unused")],
+    );
+    publish(
+        &mut harness,
+        &uri_of(&broken),
+        vec![
+            diagnostic(2, DiagnosticSeverity::ERROR, "type mismatch"),
+            diagnostic(0, DiagnosticSeverity::ERROR, "missing semicolon"),
+        ],
+    );
+    // Not a local file: listed by its URI
+    publish(
+        &mut harness,
+        "untitled:Untitled-1",
+        vec![diagnostic(0, DiagnosticSeverity::HINT, "hint")],
+    );
+
+    harness.editor_mut().open_workspace_diagnostics();
+    harness.render().unwrap();
+    harness.assert_screen_contains("E:2 W:1 I:1 in 3 files");
+    let screen = harness.screen_to_string();
+    let broken_row = screen.find("▸ broken.txt  E:2 W:0 I:0").unwrap();
+    let quiet_row = screen.find("▸ quiet.txt  E:0 W:1 I:0").unwrap();
+    let untitled_row = screen.find("▸ untitled:Untitled-1  E:0 W:0 I:1").unwrap();
+    assert!(broken_row < quiet_row && quiet_row < untitled_row);
+    harness.assert_screen_not_contains("missing semicolon");
+
+    // Enter on the first file shows its diagnostics in line order
+    press(&mut harness, KeyCode::Down, 3);
+    press(&mut harness, KeyCode::Enter, 1);
+    let screen = harness.screen_to_string();
+    let first = screen.find("1:5  E  missing semicolon").unwrap();
+    let second = screen.find("3:5  E  type mismatch").unwrap();
+    assert!(first < second);
+
+    // A publication for another file while the panel is open moves it up
+    publish(
+        &mut harness,
+        &uri_of(&quiet),
+        vec![
+            diagnostic(0, DiagnosticSeverity::ERROR, "a"),
+            diagnostic(0, DiagnosticSeverity::ERROR, "b"),
+            diagnostic(0, DiagnosticSeverity::ERROR, "c"),
+        ],
+    );
+    harness.assert_screen_contains("E:5 W:0 I:1 in 3 files");
+    let screen = harness.screen_to_string();
+    let quiet_row = screen.find("▸ quiet.txt  E:3 W:0 I:0").unwrap();
+    let broken_row = screen.find("▾ broken.txt  E:2 W:0 I:0").unwrap();
+    assert!(quiet_row < broken_row);
+
+    // The cursor stayed on broken.txt, which moved down one line; Enter on
+    // its second diagnostic jumps to it
+    press(&mut harness, KeyCode::Down, 2);
+    press(&mut harness, KeyCode::Enter, 1);
+    assert_eq!(
+        harness.editor().active_state().buffer.file_path(),
+        Some(broken.as_path())
+    );
+    let text = std::fs::read_to_string(&broken).unwrap();
+    assert_eq!(
+        harness.cursor_position(),
+        text.find("line three").unwrap() + 4
+    );
+}
@@ -513,6 +513,7 @@ fn test_overlay_undo_redo() {
 fn test_lsp_diagnostic_to_overlay() {
     use fresh::{
         config::LARGE_FILE_THRESHOLD_BYTES, model::buffer::Buffer,
+        model::position_encoding::PositionEncoding,
         services::lsp::diagnostics::diagnostic_to_overlay,
     };
     use lsp_types::{Diagnostic, DiagnosticSeverity, Position, Range};
@@ -545,7 +546,7 @@ fn test_lsp_diagnostic_to_overlay() {
     };
 
     let theme = fresh::view::theme::Theme::dark();
-    let result = diagnostic_to_overlay(&diagnostic, &buffer, &theme);
+    let result = diagnostic_to_overlay(&diagnostic, &buffer, PositionEncoding::Utf16, &theme);
     assert!(result.is_some());
 
     let (range, face, priority) = result.unwrap();
@@ -49,7 +49,7 @@ fn test_overlay_background_color_direct() {
 
     // Check the overlay face
     let overlay = overlays[0];
-    match &overlay.face {
+    match &overlay.face() {
         fresh::view::overlay::OverlayFace::Background { color } => {
             println!("Overlay color: {:?}", color);
             assert!(